@@ -10,6 +10,8 @@ pragma solidity ^0.8.20;
  * - Payment channels with dispute resolution (24h dispute period)
  * - Batch settlement for efficient multi-recipient payments
  * - Content attestation for provenance tracking
+ * - Publisher bonds, slashable on proven fraud
+ * - Fraud proof records against providers
  * - 95/5 revenue distribution support (handled off-chain, verified on-chain)
  */
 contract NodalyncSettlement {
@@ -80,6 +82,15 @@ contract NodalyncSettlement {
     /// @notice User balances (for withdrawals after settlement)
     mapping(address => uint256) public balances;
 
+    /// @notice Publisher bonds (locked stake, slashable on proven fraud)
+    mapping(address => uint256) public bonds;
+
+    /// @notice Fraud proofs recorded against providers (proof hash => provider)
+    mapping(bytes32 => address) public fraudReports;
+
+    /// @notice Number of fraud proofs recorded against each provider
+    mapping(address => uint256) public fraudCounts;
+
     // =========================================================================
     // Events
     // =========================================================================
@@ -148,6 +159,24 @@ contract NodalyncSettlement {
         uint256 amount
     );
 
+    event BondPosted(
+        address indexed publisher,
+        uint256 amount,
+        uint256 total
+    );
+
+    event BondSlashed(
+        address indexed publisher,
+        uint256 amount,
+        bytes32 evidence
+    );
+
+    event FraudReported(
+        address indexed provider,
+        address indexed reporter,
+        bytes32 proofHash
+    );
+
     // =========================================================================
     // Errors
     // =========================================================================
@@ -165,6 +194,7 @@ contract NodalyncSettlement {
     error NotParticipant(address caller, bytes32 channelId);
     error AttestationExists(bytes32 contentHash);
     error ZeroAmount();
+    error FraudAlreadyReported(bytes32 proofHash);
 
     // =========================================================================
     // Modifiers
@@ -224,6 +254,54 @@ contract NodalyncSettlement {
         emit Withdrawal(msg.sender, amount);
     }
 
+    // =========================================================================
+    // Publisher Bonds
+    // =========================================================================
+
+    /// @notice Bond part of the caller's balance as a publisher stake
+    /// @param amount Amount to bond in tinybars
+    function postBond(uint256 amount) external {
+        if (amount == 0) revert ZeroAmount();
+        if (balances[msg.sender] < amount) {
+            revert InsufficientBalance(amount, balances[msg.sender]);
+        }
+
+        balances[msg.sender] -= amount;
+        bonds[msg.sender] += amount;
+
+        emit BondPosted(msg.sender, amount, bonds[msg.sender]);
+    }
+
+    /// @notice Slash a publisher's bond on proven fraud (owner arbitrates)
+    /// @dev The slashed amount is credited to the owner's balance
+    /// @param publisher Address whose bond is slashed
+    /// @param amount Maximum amount to slash; capped at the bond
+    /// @param evidence Hash identifying the proof of fraud
+    function slashBond(address publisher, uint256 amount, bytes32 evidence) external onlyOwner {
+        if (amount == 0) revert ZeroAmount();
+        uint256 slashed = amount < bonds[publisher] ? amount : bonds[publisher];
+
+        bonds[publisher] -= slashed;
+        balances[owner] += slashed;
+
+        emit BondSlashed(publisher, slashed, evidence);
+    }
+
+    /// @notice Record a fraud proof against a provider
+    /// @dev The proof itself is distributed off-chain; only its hash is kept
+    /// @param provider Address of the provider the proof is against
+    /// @param proofHash Hash of the fraud proof
+    function reportFraud(address provider, bytes32 proofHash) external {
+        if (fraudReports[proofHash] != address(0)) {
+            revert FraudAlreadyReported(proofHash);
+        }
+
+        fraudReports[proofHash] = provider;
+        fraudCounts[provider] += 1;
+
+        emit FraudReported(provider, msg.sender, proofHash);
+    }
+
     // =========================================================================
     // Content Attestation
     // =========================================================================
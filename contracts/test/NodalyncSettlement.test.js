@@ -84,6 +84,75 @@ describe("NodalyncSettlement", function () {
     });
   });
 
+  describe("Publisher Bonds", function () {
+    const depositAmount = ethers.parseEther("1.0");
+    const bondAmount = ethers.parseEther("0.4");
+
+    beforeEach(async function () {
+      await settlement.connect(user1).deposit({ value: depositAmount });
+    });
+
+    it("Should bond part of the balance", async function () {
+      await expect(settlement.connect(user1).postBond(bondAmount))
+        .to.emit(settlement, "BondPosted")
+        .withArgs(user1.address, bondAmount, bondAmount);
+
+      expect(await settlement.bonds(user1.address)).to.equal(bondAmount);
+      expect(await settlement.balances(user1.address)).to.equal(
+        depositAmount - bondAmount
+      );
+    });
+
+    it("Should reject a bond larger than the balance", async function () {
+      await expect(
+        settlement.connect(user1).postBond(depositAmount + 1n)
+      ).to.be.revertedWithCustomError(settlement, "InsufficientBalance");
+    });
+
+    it("Should let the owner slash up to the bond", async function () {
+      const evidence = ethers.keccak256(ethers.toUtf8Bytes("bad content"));
+      await settlement.connect(user1).postBond(bondAmount);
+
+      await expect(settlement.slashBond(user1.address, depositAmount, evidence))
+        .to.emit(settlement, "BondSlashed")
+        .withArgs(user1.address, bondAmount, evidence);
+
+      expect(await settlement.bonds(user1.address)).to.equal(0);
+      expect(await settlement.balances(owner.address)).to.equal(bondAmount);
+    });
+
+    it("Should reject slashing by anyone else", async function () {
+      const evidence = ethers.keccak256(ethers.toUtf8Bytes("bad content"));
+      await settlement.connect(user1).postBond(bondAmount);
+
+      await expect(
+        settlement.connect(user2).slashBond(user1.address, bondAmount, evidence)
+      ).to.be.revertedWith("Not owner");
+    });
+  });
+
+  describe("Fraud Reports", function () {
+    const proofHash = ethers.keccak256(ethers.toUtf8Bytes("fraud proof"));
+
+    it("Should let anyone record a fraud proof", async function () {
+      await expect(settlement.connect(user2).reportFraud(user1.address, proofHash))
+        .to.emit(settlement, "FraudReported")
+        .withArgs(user1.address, user2.address, proofHash);
+
+      expect(await settlement.fraudReports(proofHash)).to.equal(user1.address);
+      expect(await settlement.fraudCounts(user1.address)).to.equal(1);
+    });
+
+    it("Should record each proof once", async function () {
+      await settlement.connect(user2).reportFraud(user1.address, proofHash);
+
+      await expect(
+        settlement.connect(user2).reportFraud(user1.address, proofHash)
+      ).to.be.revertedWithCustomError(settlement, "FraudAlreadyReported");
+      expect(await settlement.fraudCounts(user1.address)).to.equal(1);
+    });
+  });
+
   describe("Content Attestation", function () {
     it("Should create attestation", async function () {
       const contentHash = ethers.keccak256(ethers.toUtf8Bytes("test content"));
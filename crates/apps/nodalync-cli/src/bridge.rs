@@ -0,0 +1,1036 @@
+//! RSS/Atom and ActivityPub bridge for content announcements.
+//!
+//! Helps content be discovered outside the P2P network, in both directions:
+//!
+//! - **Publishing**: the node's Shared content is served as RSS and Atom
+//!   feeds and as a read-only ActivityPub actor whose outbox holds one
+//!   `Create`/`Note` per item. Every item carries its title, price and a link
+//!   to a preview page. The actor takes no follows; fediverse servers read
+//!   its outbox.
+//! - **Ingesting**: configured external feeds are polled, and their items are
+//!   stored as unpaid announcements with no publisher to query. They show up
+//!   in search and preview like gossiped content, with a link to the source.
+//!
+//! # Routes
+//!
+//! - `GET /feed.rss` - RSS 2.0 feed
+//! - `GET /feed.atom` - Atom feed
+//! - `GET /.well-known/webfinger?resource=acct:<name>@<host>` - Actor lookup
+//! - `GET /actor` - ActivityPub actor (`Service`)
+//! - `GET /outbox` - ActivityPub outbox (`OrderedCollection`)
+//! - `GET /content/<hash>` - HTML preview page
+
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use nodalync_crypto::{content_hash, public_key_to_multibase, PublicKey};
+use nodalync_ops::{truncate_string, DefaultNodeOperations};
+use nodalync_store::{AnnouncementWriter, ManifestFilter, ManifestStore};
+use nodalync_types::{
+    ContentType, L1Summary, Manifest, Visibility, MAX_PRIMARY_TOPICS, MAX_SUMMARY_LENGTH,
+    MAX_TITLE_LENGTH,
+};
+use nodalync_wire::AnnouncePayload;
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tokio::time::interval;
+use tracing::{debug, info, warn};
+
+use crate::config::{format_hbar, BridgeConfig};
+use crate::error::{CliError, CliResult};
+use crate::logging::format_timestamp;
+
+/// Largest external feed fetched, in bytes.
+const MAX_FEED_BYTES: usize = 4 * 1024 * 1024;
+
+/// Timeout for fetching an external feed.
+const FEED_FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// ActivityStreams public collection.
+const AS_PUBLIC: &str = "https://www.w3.org/ns/activitystreams#Public";
+
+// =============================================================================
+// Published Feed
+// =============================================================================
+
+/// What the bridge publishes as, built once at startup.
+#[derive(Debug, Clone)]
+pub struct BridgeSite {
+    /// Public base URL, without a trailing slash.
+    pub base_url: String,
+    /// ActivityPub username.
+    pub actor_name: String,
+    /// Feed and actor title.
+    pub title: String,
+    /// Our peer ID (`ndl1...`).
+    pub peer_id: String,
+    /// Our identity key in multibase form, if known.
+    pub public_key_multibase: Option<String>,
+}
+
+impl BridgeSite {
+    /// Describe a node from its bridge configuration.
+    pub fn new(config: &BridgeConfig, peer_id: String, public_key: Option<&PublicKey>) -> Self {
+        Self {
+            base_url: config.base_url(),
+            actor_name: config.actor_name.clone(),
+            title: config
+                .title
+                .clone()
+                .unwrap_or_else(|| format!("Nodalync node {}", peer_id)),
+            peer_id,
+            public_key_multibase: public_key.map(public_key_to_multibase),
+        }
+    }
+
+    /// Host part of the base URL, as used in `acct:` URIs.
+    pub fn host(&self) -> &str {
+        let rest = self
+            .base_url
+            .split_once("://")
+            .map_or(self.base_url.as_str(), |(_, rest)| rest);
+        rest.split('/').next().unwrap_or(rest)
+    }
+
+    fn actor_url(&self) -> String {
+        format!("{}/actor", self.base_url)
+    }
+
+    fn content_url(&self, hash: &str) -> String {
+        format!("{}/content/{}", self.base_url, hash)
+    }
+}
+
+/// One item of our feeds, taken from a Shared manifest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedEntry {
+    /// Content hash (hex).
+    pub hash: String,
+    /// Content title.
+    pub title: String,
+    /// Content description.
+    pub description: Option<String>,
+    /// Content tags.
+    pub tags: Vec<String>,
+    /// Query price (tinybars).
+    pub price: u64,
+    /// Creation time (Unix milliseconds).
+    pub published: u64,
+    /// Last update time (Unix milliseconds).
+    pub updated: u64,
+}
+
+impl From<&Manifest> for FeedEntry {
+    fn from(manifest: &Manifest) -> Self {
+        Self {
+            hash: manifest.hash.to_string(),
+            title: manifest.metadata.title.clone(),
+            description: manifest.metadata.description.clone(),
+            tags: manifest.metadata.tags.clone(),
+            price: manifest.economics.price,
+            published: manifest.created_at,
+            updated: manifest.updated_at,
+        }
+    }
+}
+
+/// Feed entries shared between the event loop and the bridge server.
+pub type SharedFeed = Arc<RwLock<Vec<FeedEntry>>>;
+
+/// Our newest Shared content, up to `max_items`.
+pub fn shared_entries(ops: &DefaultNodeOperations, max_items: u32) -> CliResult<Vec<FeedEntry>> {
+    let filter = ManifestFilter::new()
+        .with_visibility(Visibility::Shared)
+        .with_owner(ops.peer_id())
+        .limit(max_items);
+    let manifests = ops.state.manifests.list(filter)?;
+    Ok(manifests.iter().map(FeedEntry::from).collect())
+}
+
+/// Replace the served entries with our current Shared content.
+pub fn refresh_feed(ops: &DefaultNodeOperations, feed: &SharedFeed, max_items: u32) {
+    match shared_entries(ops, max_items) {
+        Ok(entries) => {
+            if let Ok(mut feed) = feed.write() {
+                *feed = entries;
+            }
+        }
+        Err(e) => warn!(error = %e, "Failed to refresh bridge feed"),
+    }
+}
+
+/// Render entries as an RSS 2.0 feed.
+pub fn render_rss(site: &BridgeSite, entries: &[FeedEntry]) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\">\n<channel>\n",
+    );
+    xml.push_str(&format!(
+        "<title>{}</title>\n<link>{}/</link>\n<description>Content shared by {}</description>\n",
+        escape_xml(&site.title),
+        escape_xml(&site.base_url),
+        escape_xml(&site.peer_id)
+    ));
+    xml.push_str(&format!(
+        "<atom:link href=\"{}/feed.rss\" rel=\"self\" type=\"application/rss+xml\"/>\n",
+        escape_xml(&site.base_url)
+    ));
+    if let Some(entry) = entries.iter().max_by_key(|e| e.updated) {
+        xml.push_str(&format!(
+            "<lastBuildDate>{}</lastBuildDate>\n",
+            rfc2822(entry.updated)
+        ));
+    }
+
+    for entry in entries {
+        let link = escape_xml(&site.content_url(&entry.hash));
+        xml.push_str("<item>\n");
+        xml.push_str(&format!("<title>{}</title>\n", escape_xml(&entry.title)));
+        xml.push_str(&format!("<link>{}</link>\n", link));
+        xml.push_str(&format!(
+            "<guid isPermaLink=\"false\">{}</guid>\n",
+            entry.hash
+        ));
+        xml.push_str(&format!(
+            "<description>{}</description>\n",
+            escape_xml(&entry_summary(entry))
+        ));
+        for tag in &entry.tags {
+            xml.push_str(&format!("<category>{}</category>\n", escape_xml(tag)));
+        }
+        xml.push_str(&format!(
+            "<pubDate>{}</pubDate>\n",
+            rfc2822(entry.published)
+        ));
+        xml.push_str("</item>\n");
+    }
+
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+/// Render entries as an Atom feed.
+pub fn render_atom(site: &BridgeSite, entries: &[FeedEntry]) -> String {
+    let updated = entries.iter().map(|e| e.updated).max().unwrap_or(0);
+    let base = escape_xml(&site.base_url);
+
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <feed xmlns=\"http://www.w3.org/2005/Atom\">\n",
+    );
+    xml.push_str(&format!(
+        "<id>{}/feed.atom</id>\n<title>{}</title>\n<updated>{}</updated>\n",
+        base,
+        escape_xml(&site.title),
+        rfc3339(updated)
+    ));
+    xml.push_str(&format!(
+        "<link rel=\"self\" href=\"{}/feed.atom\"/>\n<link rel=\"alternate\" href=\"{}/\"/>\n",
+        base, base
+    ));
+    xml.push_str(&format!(
+        "<author><name>{}</name><uri>{}</uri></author>\n",
+        escape_xml(&site.peer_id),
+        escape_xml(&site.actor_url())
+    ));
+
+    for entry in entries {
+        let link = escape_xml(&site.content_url(&entry.hash));
+        xml.push_str("<entry>\n");
+        xml.push_str(&format!("<id>urn:nodalync:{}</id>\n", entry.hash));
+        xml.push_str(&format!("<title>{}</title>\n", escape_xml(&entry.title)));
+        xml.push_str(&format!("<link rel=\"alternate\" href=\"{}\"/>\n", link));
+        xml.push_str(&format!(
+            "<published>{}</published>\n",
+            rfc3339(entry.published)
+        ));
+        xml.push_str(&format!("<updated>{}</updated>\n", rfc3339(entry.updated)));
+        xml.push_str(&format!(
+            "<summary>{}</summary>\n",
+            escape_xml(&entry_summary(entry))
+        ));
+        for tag in &entry.tags {
+            xml.push_str(&format!("<category term=\"{}\"/>\n", escape_xml(tag)));
+        }
+        xml.push_str("</entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+/// The bridge's ActivityPub actor.
+///
+/// The identity key is listed as a `Multikey` assertion method so readers
+/// can tie the actor to the node. There is no shared inbox: the actor
+/// accepts no activities.
+pub fn actor_document(site: &BridgeSite) -> Value {
+    let actor = site.actor_url();
+    let mut document = json!({
+        "@context": [
+            "https://www.w3.org/ns/activitystreams",
+            "https://w3id.org/security/multikey/v1"
+        ],
+        "id": actor,
+        "type": "Service",
+        "preferredUsername": site.actor_name,
+        "name": site.title,
+        "summary": format!("Nodalync node {}", site.peer_id),
+        "url": format!("{}/", site.base_url),
+        "inbox": format!("{}/inbox", site.base_url),
+        "outbox": format!("{}/outbox", site.base_url),
+        "manuallyApprovesFollowers": true,
+        "discoverable": true
+    });
+    if let Some(ref key) = site.public_key_multibase {
+        document["assertionMethod"] = json!([{
+            "id": format!("{}#identity-key", actor),
+            "type": "Multikey",
+            "controller": actor,
+            "publicKeyMultibase": key
+        }]);
+    }
+    document
+}
+
+/// The actor's outbox, one `Create` activity per entry.
+pub fn outbox_document(site: &BridgeSite, entries: &[FeedEntry]) -> Value {
+    let actor = site.actor_url();
+    let items: Vec<Value> = entries
+        .iter()
+        .map(|entry| {
+            let url = site.content_url(&entry.hash);
+            let tags: Vec<Value> = entry
+                .tags
+                .iter()
+                .map(|tag| json!({ "type": "Hashtag", "name": format!("#{}", tag) }))
+                .collect();
+            json!({
+                "id": format!("{}#create", url),
+                "type": "Create",
+                "actor": actor,
+                "published": rfc3339(entry.published),
+                "to": [AS_PUBLIC],
+                "object": {
+                    "id": url,
+                    "type": "Note",
+                    "attributedTo": actor,
+                    "name": entry.title,
+                    "content": note_content(&url, entry),
+                    "url": url,
+                    "published": rfc3339(entry.published),
+                    "updated": rfc3339(entry.updated),
+                    "to": [AS_PUBLIC],
+                    "tag": tags
+                }
+            })
+        })
+        .collect();
+
+    json!({
+        "@context": "https://www.w3.org/ns/activitystreams",
+        "id": format!("{}/outbox", site.base_url),
+        "type": "OrderedCollection",
+        "totalItems": items.len(),
+        "orderedItems": items
+    })
+}
+
+/// WebFinger response for `resource`, or `None` if it isn't our actor.
+pub fn webfinger_document(site: &BridgeSite, resource: &str) -> Option<Value> {
+    let subject = format!("acct:{}@{}", site.actor_name, site.host());
+    if resource != subject && resource != site.actor_url() {
+        return None;
+    }
+    Some(json!({
+        "subject": subject,
+        "aliases": [site.actor_url()],
+        "links": [{
+            "rel": "self",
+            "type": "application/activity+json",
+            "href": site.actor_url()
+        }]
+    }))
+}
+
+/// HTML preview page for one entry.
+pub fn preview_page(site: &BridgeSite, entry: &FeedEntry) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{}</title>\n\
+         <link rel=\"alternate\" type=\"application/activity+json\" href=\"{}\">\n</head><body>\n\
+         <h1>{}</h1>\n",
+        escape_xml(&entry.title),
+        escape_xml(&site.content_url(&entry.hash)),
+        escape_xml(&entry.title)
+    );
+    if let Some(ref description) = entry.description {
+        html.push_str(&format!("<p>{}</p>\n", escape_xml(description)));
+    }
+    html.push_str(&format!(
+        "<p>Price: {} per query</p>\n",
+        format_hbar(entry.price)
+    ));
+    if !entry.tags.is_empty() {
+        html.push_str(&format!(
+            "<p>Tags: {}</p>\n",
+            escape_xml(&entry.tags.join(", "))
+        ));
+    }
+    html.push_str(&format!(
+        "<p>Published by <code>{}</code>. Preview or query it with:</p>\n\
+         <pre>nodalync preview {}\nnodalync query {}</pre>\n</body></html>\n",
+        escape_xml(&site.peer_id),
+        entry.hash,
+        entry.hash
+    ));
+    html
+}
+
+/// A bridge HTTP response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BridgeResponse {
+    /// HTTP status code.
+    pub status: u16,
+    /// Content-Type header.
+    pub content_type: &'static str,
+    /// Response body.
+    pub body: String,
+}
+
+impl BridgeResponse {
+    fn ok(content_type: &'static str, body: String) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            body,
+        }
+    }
+
+    fn json(content_type: &'static str, value: &Value) -> Self {
+        Self::ok(content_type, value.to_string())
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: format!("{}\n", message),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Answer a `GET` for `target` (path and query).
+pub fn route(site: &BridgeSite, entries: &[FeedEntry], target: &str) -> BridgeResponse {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    match path {
+        "/feed.rss" => BridgeResponse::ok(
+            "application/rss+xml; charset=utf-8",
+            render_rss(site, entries),
+        ),
+        "/feed.atom" => BridgeResponse::ok(
+            "application/atom+xml; charset=utf-8",
+            render_atom(site, entries),
+        ),
+        "/actor" => BridgeResponse::json("application/activity+json", &actor_document(site)),
+        "/outbox" => {
+            BridgeResponse::json("application/activity+json", &outbox_document(site, entries))
+        }
+        "/inbox" => BridgeResponse::error(405, "This actor accepts no activities"),
+        "/.well-known/webfinger" => {
+            let Some(resource) = query_param(query, "resource") else {
+                return BridgeResponse::error(400, "Missing resource parameter");
+            };
+            match webfinger_document(site, &resource) {
+                Some(document) => BridgeResponse::json("application/jrd+json", &document),
+                None => BridgeResponse::error(404, "Unknown resource"),
+            }
+        }
+        _ => {
+            let entry = path
+                .strip_prefix("/content/")
+                .and_then(|hash| entries.iter().find(|e| e.hash == hash));
+            match entry {
+                Some(entry) => {
+                    BridgeResponse::ok("text/html; charset=utf-8", preview_page(site, entry))
+                }
+                None => BridgeResponse::error(404, "Not found"),
+            }
+        }
+    }
+}
+
+/// Serve the bridge routes over HTTP until shutdown.
+pub async fn run_bridge_server(
+    listen: String,
+    site: BridgeSite,
+    feed: SharedFeed,
+    mut shutdown_rx: watch::Receiver<bool>,
+) -> CliResult<()> {
+    let listener = TcpListener::bind(&listen).await.map_err(|e| {
+        CliError::config(format!("Failed to bind bridge server to {}: {}", listen, e))
+    })?;
+
+    info!("Bridge server listening on {}", listen);
+
+    loop {
+        tokio::select! {
+            // Check for shutdown
+            result = shutdown_rx.changed() => {
+                if result.is_err() || *shutdown_rx.borrow() {
+                    info!("Bridge server shutting down");
+                    break;
+                }
+            }
+
+            // Accept connections
+            accept_result = listener.accept() => {
+                match accept_result {
+                    Ok((mut socket, _)) => {
+                        let mut buf = [0u8; 2048];
+                        let n = socket.read(&mut buf).await.unwrap_or(0);
+                        let request = String::from_utf8_lossy(&buf[..n]);
+
+                        // Request line, e.g. "GET /feed.rss HTTP/1.1"
+                        let mut parts = request
+                            .lines()
+                            .next()
+                            .unwrap_or_default()
+                            .split_whitespace();
+                        let method = parts.next().unwrap_or_default();
+                        let target = parts.next().unwrap_or("/");
+
+                        let response = if method == "GET" || method == "HEAD" {
+                            let entries = feed.read().map(|e| e.clone()).unwrap_or_default();
+                            route(&site, &entries, target)
+                        } else {
+                            BridgeResponse::error(405, "Only GET is supported")
+                        };
+
+                        let head = format!(
+                            "HTTP/1.1 {} {}\r\n\
+                             Content-Type: {}\r\n\
+                             Content-Length: {}\r\n\
+                             Access-Control-Allow-Origin: *\r\n\
+                             Connection: close\r\n\
+                             \r\n",
+                            response.status,
+                            response.reason(),
+                            response.content_type,
+                            response.body.len()
+                        );
+
+                        // Ignore errors, the client may have disconnected
+                        let _ = socket.write_all(head.as_bytes()).await;
+                        if method != "HEAD" {
+                            let _ = socket.write_all(response.body.as_bytes()).await;
+                        }
+                    }
+                    Err(e) => {
+                        debug!("Bridge server accept error: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// =============================================================================
+// External Feed Ingestion
+// =============================================================================
+
+/// An item read from an external RSS or Atom feed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalItem {
+    /// Item title.
+    pub title: String,
+    /// Link to the item's page.
+    pub link: Option<String>,
+    /// RSS `guid` or Atom `id`.
+    pub id: Option<String>,
+    /// Description or summary, with markup removed.
+    pub summary: String,
+    /// Categories.
+    pub categories: Vec<String>,
+}
+
+/// Parse an RSS 2.0 or Atom document into its items.
+///
+/// Items with no title, or with neither a link nor an ID, are skipped.
+pub fn parse_feed(xml: &str) -> Result<Vec<ExternalItem>, String> {
+    let doc = roxmltree::Document::parse(xml).map_err(|e| e.to_string())?;
+
+    let items = doc
+        .descendants()
+        .filter(|n| n.is_element() && matches!(n.tag_name().name(), "item" | "entry"))
+        .filter_map(|node| {
+            let child = |name: &str| {
+                node.children()
+                    .find(|c| c.is_element() && c.tag_name().name() == name)
+            };
+            let text = |name: &str| {
+                child(name)
+                    .and_then(|c| c.text())
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+            };
+
+            let title = text("title").map(|t| strip_markup(&t))?;
+            // Atom links carry the URL in `href`, preferring rel="alternate"
+            let link = text("link").or_else(|| {
+                node.children()
+                    .filter(|c| c.is_element() && c.tag_name().name() == "link")
+                    .find(|c| c.attribute("rel").is_none_or(|rel| rel == "alternate"))
+                    .and_then(|c| c.attribute("href"))
+                    .map(str::to_string)
+            });
+            let id = text("guid").or_else(|| text("id"));
+            if link.is_none() && id.is_none() {
+                return None;
+            }
+            let summary = text("description")
+                .or_else(|| text("summary"))
+                .or_else(|| text("content"))
+                .map(|s| strip_markup(&s))
+                .unwrap_or_default();
+            let categories = node
+                .children()
+                .filter(|c| c.is_element() && c.tag_name().name() == "category")
+                .filter_map(|c| {
+                    c.attribute("term")
+                        .map(str::to_string)
+                        .or_else(|| c.text().map(|t| t.trim().to_string()))
+                })
+                .filter(|c| !c.is_empty())
+                .collect();
+
+            Some(ExternalItem {
+                title,
+                link,
+                id,
+                summary,
+                categories,
+            })
+        })
+        .collect();
+
+    Ok(items)
+}
+
+/// Turn an external item into an unpaid, preview-only announcement.
+///
+/// The hash is derived from the item's ID (or link) so re-polling a feed
+/// updates the same announcement. There is no publisher and no address, so
+/// the item can be previewed but never queried; the summary links to the
+/// source instead.
+pub fn item_announcement(item: &ExternalItem) -> AnnouncePayload {
+    let key = item
+        .id
+        .as_deref()
+        .or(item.link.as_deref())
+        .unwrap_or(&item.title);
+    let hash = content_hash(format!("nodalync-bridge:{}", key).as_bytes());
+
+    let summary = match item.link {
+        Some(ref link) => {
+            let source = format!("Source: {}", link);
+            let room = MAX_SUMMARY_LENGTH.saturating_sub(source.len() + 2);
+            if item.summary.is_empty() || room < 4 {
+                truncate_string(&source, MAX_SUMMARY_LENGTH)
+            } else {
+                format!("{}\n\n{}", truncate_string(&item.summary, room), source)
+            }
+        }
+        None => truncate_string(&item.summary, MAX_SUMMARY_LENGTH),
+    };
+    let topics = item
+        .categories
+        .iter()
+        .take(MAX_PRIMARY_TOPICS)
+        .cloned()
+        .collect();
+
+    AnnouncePayload {
+        hash,
+        content_type: ContentType::L0,
+        title: truncate_string(&item.title, MAX_TITLE_LENGTH),
+        l1_summary: L1Summary::new(hash, 0, Vec::new(), topics, summary),
+        price: 0,
+        addresses: Vec::new(),
+        publisher_peer_id: None,
+        publisher_key: None,
+        signature: None,
+        delegation: None,
+    }
+}
+
+/// Fetch an external feed, refusing bodies over [`MAX_FEED_BYTES`].
+async fn fetch_feed(client: &reqwest::Client, url: &str) -> Result<String, String> {
+    let mut response = client.get(url).send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("status {}", response.status()));
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        if body.len() + chunk.len() > MAX_FEED_BYTES {
+            return Err(format!("feed larger than {} bytes", MAX_FEED_BYTES));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    String::from_utf8(body).map_err(|e| e.to_string())
+}
+
+/// Poll every feed once, storing their items as announcements.
+///
+/// Returns how many announcements were stored.
+pub async fn poll_feeds(
+    client: &reqwest::Client,
+    feeds: &[String],
+    writer: &AnnouncementWriter,
+) -> usize {
+    let mut stored = 0;
+    for url in feeds {
+        let items = match fetch_feed(client, url)
+            .await
+            .and_then(|xml| parse_feed(&xml))
+        {
+            Ok(items) => items,
+            Err(e) => {
+                warn!(feed = %url, error = %e, "Failed to read external feed");
+                continue;
+            }
+        };
+        let payloads: Vec<AnnouncePayload> = items.iter().map(item_announcement).collect();
+        let count = writer.store(&payloads);
+        debug!(feed = %url, items = count, "Ingested external feed");
+        stored += count;
+    }
+    stored
+}
+
+/// Poll external feeds on an interval until shutdown.
+pub async fn run_feed_ingest(
+    feeds: Vec<String>,
+    poll_interval: Duration,
+    writer: AnnouncementWriter,
+    mut shutdown_rx: watch::Receiver<bool>,
+) {
+    let client = reqwest::Client::builder()
+        .timeout(FEED_FETCH_TIMEOUT)
+        .user_agent(concat!("nodalync/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .unwrap_or_default();
+    let mut poll_interval = interval(poll_interval);
+
+    loop {
+        tokio::select! {
+            result = shutdown_rx.changed() => {
+                if result.is_err() || *shutdown_rx.borrow() {
+                    break;
+                }
+            }
+            _ = poll_interval.tick() => {
+                let stored = poll_feeds(&client, &feeds, &writer).await;
+                info!(feeds = feeds.len(), stored, "Polled external feeds");
+            }
+        }
+    }
+}
+
+// =============================================================================
+// Helpers
+// =============================================================================
+
+/// Escape text for XML and HTML.
+pub fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Remove markup and common entities from feed text, collapsing whitespace.
+fn strip_markup(s: &str) -> String {
+    let mut text = String::with_capacity(s.len());
+    let mut in_tag = false;
+    for c in s.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Plain-text summary of an entry: its description and price.
+fn entry_summary(entry: &FeedEntry) -> String {
+    let price = format!("Price: {} per query", format_hbar(entry.price));
+    match entry.description {
+        Some(ref description) if !description.is_empty() => {
+            format!("{}\n\n{}", description, price)
+        }
+        _ => price,
+    }
+}
+
+/// HTML body of an entry's ActivityPub note.
+fn note_content(url: &str, entry: &FeedEntry) -> String {
+    let mut content = format!("<p><strong>{}</strong></p>", escape_xml(&entry.title));
+    if let Some(ref description) = entry.description {
+        content.push_str(&format!("<p>{}</p>", escape_xml(description)));
+    }
+    content.push_str(&format!(
+        "<p>Price: {} per query. <a href=\"{}\">Preview</a></p>",
+        format_hbar(entry.price),
+        escape_xml(url)
+    ));
+    content
+}
+
+/// Decode a query parameter (percent-encoding and `+`).
+fn query_param(query: &str, name: &str) -> Option<String> {
+    let value = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)?;
+
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok()?;
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            }
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            b => {
+                decoded.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Format a Unix timestamp (milliseconds) as RFC 3339 in UTC.
+fn rfc3339(ms: u64) -> String {
+    format!("{}Z", format_timestamp(ms).replacen(' ', "T", 1))
+}
+
+/// Format a Unix timestamp (milliseconds) as RFC 2822 in UTC, for RSS.
+fn rfc2822(ms: u64) -> String {
+    const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    // "YYYY-MM-DD HH:MM:SS.mmm"; 1970-01-01 was a Thursday
+    let stamp = format_timestamp(ms);
+    let month: usize = stamp[5..7].parse().unwrap_or(1);
+    let weekday = WEEKDAYS[((ms / 1000 / 86_400) % 7) as usize];
+    format!(
+        "{}, {} {} {} {} +0000",
+        weekday,
+        &stamp[8..10],
+        MONTHS[month.clamp(1, 12) - 1],
+        &stamp[0..4],
+        &stamp[11..19]
+    )
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn site() -> BridgeSite {
+        let config = BridgeConfig {
+            public_url: Some("https://node.example.com/".to_string()),
+            ..Default::default()
+        };
+        BridgeSite::new(&config, "ndl1test".to_string(), None)
+    }
+
+    fn entry() -> FeedEntry {
+        FeedEntry {
+            hash: "ab".repeat(32),
+            title: "Rust & Protocols".to_string(),
+            description: Some("Notes on <wire> formats".to_string()),
+            tags: vec!["rust".to_string()],
+            price: 10_000_000,
+            published: 1_700_000_000_000,
+            updated: 1_700_000_060_000,
+        }
+    }
+
+    #[test]
+    fn test_dates() {
+        // 2023-11-14 22:13:20 UTC, a Tuesday
+        assert_eq!(rfc3339(1_700_000_000_000), "2023-11-14T22:13:20.000Z");
+        assert_eq!(
+            rfc2822(1_700_000_000_000),
+            "Tue, 14 Nov 2023 22:13:20 +0000"
+        );
+        assert_eq!(rfc2822(0), "Thu, 01 Jan 1970 00:00:00 +0000");
+    }
+
+    #[test]
+    fn test_render_feeds_parse_back() {
+        let site = site();
+        let entries = vec![entry()];
+
+        for xml in [render_rss(&site, &entries), render_atom(&site, &entries)] {
+            let items = parse_feed(&xml).unwrap();
+            assert_eq!(items.len(), 1);
+            assert_eq!(items[0].title, "Rust & Protocols");
+            assert_eq!(
+                items[0].link.as_deref(),
+                Some(format!("https://node.example.com/content/{}", "ab".repeat(32)).as_str())
+            );
+            assert!(items[0].summary.contains("Price: 0.1000 HBAR per query"));
+            assert_eq!(items[0].categories, vec!["rust"]);
+        }
+    }
+
+    #[test]
+    fn test_parse_external_feeds() {
+        let rss = r#"<?xml version="1.0"?>
+            <rss version="2.0"><channel><title>Blog</title>
+              <item><title>First</title><link>https://blog.example/1</link>
+                <description>&lt;p&gt;Hello &amp;amp; welcome&lt;/p&gt;</description>
+                <category>news</category></item>
+              <item><description>no title</description></item>
+            </channel></rss>"#;
+        let items = parse_feed(rss).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].summary, "Hello & welcome");
+        assert_eq!(items[0].categories, vec!["news"]);
+
+        let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+              <entry><id>tag:example,2024:1</id><title>Paper</title>
+                <link rel="self" href="https://example/self"/>
+                <link href="https://example/paper"/>
+                <summary>Abstract</summary></entry>
+            </feed>"#;
+        let items = parse_feed(atom).unwrap();
+        assert_eq!(items[0].link.as_deref(), Some("https://example/paper"));
+        assert_eq!(items[0].id.as_deref(), Some("tag:example,2024:1"));
+
+        assert!(parse_feed("not xml").is_err());
+    }
+
+    #[test]
+    fn test_item_announcement() {
+        let item = ExternalItem {
+            title: "Paper".to_string(),
+            link: Some("https://example/paper".to_string()),
+            id: Some("tag:example,2024:1".to_string()),
+            summary: "x".repeat(1000),
+            categories: (0..10).map(|i| i.to_string()).collect(),
+        };
+        let payload = item_announcement(&item);
+
+        assert_eq!(payload.price, 0);
+        assert!(payload.addresses.is_empty());
+        assert!(payload.publisher_peer_id.is_none());
+        assert!(payload.l1_summary.summary.len() <= MAX_SUMMARY_LENGTH);
+        assert!(payload
+            .l1_summary
+            .summary
+            .ends_with("Source: https://example/paper"));
+        assert_eq!(payload.l1_summary.primary_topics.len(), MAX_PRIMARY_TOPICS);
+
+        // Re-polling yields the same announcement
+        assert_eq!(item_announcement(&item).hash, payload.hash);
+    }
+
+    #[test]
+    fn test_route() {
+        let site = site();
+        let entries = vec![entry()];
+
+        let response = route(&site, &entries, "/feed.rss");
+        assert_eq!(response.status, 200);
+        assert!(response.body.contains("Rust &amp; Protocols"));
+
+        let response = route(
+            &site,
+            &entries,
+            "/.well-known/webfinger?resource=acct%3Anodalync%40node.example.com",
+        );
+        assert_eq!(response.status, 200);
+        let document: Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(
+            document["links"][0]["href"],
+            "https://node.example.com/actor"
+        );
+
+        let response = route(&site, &entries, "/outbox");
+        let document: Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(document["totalItems"], 1);
+        assert_eq!(
+            document["orderedItems"][0]["object"]["name"],
+            "Rust & Protocols"
+        );
+
+        let response = route(&site, &entries, &format!("/content/{}", "ab".repeat(32)));
+        assert_eq!(response.status, 200);
+        assert!(response.body.contains("&lt;wire&gt;"));
+
+        assert_eq!(route(&site, &entries, "/content/unknown").status, 404);
+        assert_eq!(
+            route(
+                &site,
+                &entries,
+                "/.well-known/webfinger?resource=acct:other@x"
+            )
+            .status,
+            404
+        );
+    }
+}
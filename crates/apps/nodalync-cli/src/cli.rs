@@ -1,6 +1,7 @@
 //! CLI argument definitions using clap.
 
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use nodalync_store::{DigestPeriod, InvoiceDirection, LedgerAccount, WebhookDeliveryStatus};
 use std::path::PathBuf;
 
 use crate::output::OutputFormat;
@@ -61,115 +62,119 @@ pub enum Commands {
     ///
     /// Creates a new Ed25519 keypair and stores it encrypted.
     /// Also creates a default configuration file.
-    Init {
-        /// Run interactive setup wizard.
-        #[arg(short, long)]
-        wizard: bool,
-    },
+    Init(InitArgs),
 
     /// Show identity information.
     ///
-    /// Displays the PeerId, public key, and listening addresses.
+    /// Displays the PeerId, public key, DID, and listening addresses.
     Whoami,
 
+    /// Show your DID document.
+    ///
+    /// Your identity as a `did:key` DID, for use with verifiable-credential
+    /// tooling.
+    Did(DidArgs),
+
+    /// Resolve another peer's DID to its PeerId and public key.
+    ResolveDid(ResolveDidArgs),
+
+    /// Verify a DID document received from another peer.
+    VerifyDid(VerifyDidArgs),
+
+    /// Revoke an identity key with its revocation certificate.
+    ///
+    /// Use the certificate written by `nodalync init` if the key leaks.
+    /// Peers stop accepting messages and content signed by the key.
+    Revoke(RevokeArgs),
+
     // =========================================================================
     // Content Management Commands
     // =========================================================================
     /// Publish content to the network.
     ///
     /// Hashes the file, extracts L1 mentions, and announces to the DHT.
-    Publish {
-        /// Path to the file to publish.
-        file: PathBuf,
-
-        /// Price per query in HBAR (default from config).
-        #[arg(short, long, allow_hyphen_values = true, value_parser = parse_non_negative_price)]
-        price: Option<f64>,
-
-        /// Visibility level.
-        #[arg(short = 'V', long, default_value = "shared")]
-        visibility: VisibilityArg,
-
-        /// Title for the content (defaults to filename).
-        #[arg(short, long)]
-        title: Option<String>,
+    Publish(PublishArgs),
 
-        /// Description for the content.
-        #[arg(short, long)]
-        description: Option<String>,
-    },
+    /// Import content from another network as L0 content.
+    Import(ImportArgs),
 
     /// List local content.
     ///
     /// Shows all content stored locally, grouped by visibility.
-    List {
-        /// Filter by visibility level.
-        #[arg(short = 'V', long)]
-        visibility: Option<VisibilityArg>,
+    List(ListArgs),
 
-        /// Filter by content type.
-        #[arg(short = 't', long)]
-        content_type: Option<ContentTypeArg>,
+    /// Update content (create a new version).
+    ///
+    /// Creates a new version linked to the previous content.
+    Update(UpdateArgs),
 
-        /// Maximum results to show.
-        #[arg(short, long, default_value = "50")]
-        limit: u32,
+    /// Change content visibility.
+    ///
+    /// Updates how content is discovered and served.
+    Visibility(VisibilityArgs),
 
-        /// Include content available from network peers.
-        #[arg(short = 'n', long)]
-        network: bool,
-    },
+    /// Redact parts of the preview peers see before paying.
+    ///
+    /// Replaces the redaction rules when any are given; without options,
+    /// shows the current rules. Re-publish to update announcements peers
+    /// already hold.
+    PreviewPolicy(PreviewPolicyArgs),
 
-    /// Update content (create a new version).
+    /// Set a dynamic price that surges with demand or decays over time.
     ///
-    /// Creates a new version linked to the previous content.
-    Update {
-        /// Hash of the content to update.
-        hash: String,
+    /// The content's price stays the floor the rule moves up from. Without
+    /// options, shows the current rule and price.
+    Pricing(PricingArgs),
 
-        /// Path to the new file.
-        file: PathBuf,
+    /// Sell sections of Markdown content at their own prices.
+    ///
+    /// A section runs from its heading to the next heading of the same or
+    /// a higher level. Replaces the sections when any prices are given;
+    /// without options, lists the current ones.
+    Sections(SectionsArgs),
 
-        /// New title (optional).
-        #[arg(short, long)]
-        title: Option<String>,
+    /// Sell content in metered units, paid for as they are read.
+    ///
+    /// The price is spread evenly across the units, so a reader that stops
+    /// early pays only for what it read. Without options, shows the current
+    /// units.
+    Metering(MeteringArgs),
 
-        /// Price per query in HBAR (defaults to previous version's price).
-        #[arg(short, long, allow_hyphen_values = true, value_parser = parse_non_negative_price)]
-        price: Option<f64>,
-    },
+    /// Offer free trial reads of paid content.
+    ///
+    /// Each peer may read the content free a few times, or only its first
+    /// bytes, before paying. Without options, shows the current trial.
+    Trial(TrialArgs),
 
-    /// Change content visibility.
+    /// Limit how much each peer may query content per day.
     ///
-    /// Updates how content is discovered and served.
-    Visibility {
-        /// Hash of the content.
-        hash: String,
+    /// Each peer may make this many queries, or be served this many bytes,
+    /// per UTC day, and is refused until the next day once it runs out.
+    /// Setting a quota replaces the old one. Without options, shows the
+    /// quota and today's usage. The node-wide quota is set in `[ops.quota]`.
+    Quota(QuotaArgs),
 
-        /// New visibility level (private, unlisted, or shared).
-        #[arg(short, long, alias = "visibility")]
-        level: VisibilityArg,
-    },
+    /// Offer a cut of each payment to whoever refers a query.
+    ///
+    /// Search nodes and recommenders that send paying queries to the
+    /// content are paid the cut. Without options, shows the current cut.
+    Referral(ReferralArgs),
+
+    /// Share content with a capability token.
+    ///
+    /// Prints a signed token that lets its holder query the content
+    /// directly from this node, even while it is private.
+    Share(ShareArgs),
 
     /// Show all versions of content.
     ///
-    /// Lists the complete version history.
-    Versions {
-        /// Hash of any version in the chain.
-        hash: String,
-    },
+    /// Lists the complete version history, and any gaps or forks in it.
+    Versions(VersionsArgs),
 
     /// Delete local content.
     ///
     /// Removes the local copy but preserves provenance records.
-    Delete {
-        /// Hash of the content to delete.
-        hash: String,
-
-        /// Skip confirmation prompt.
-        #[arg(short = 'F', long)]
-        force: bool,
-    },
+    Delete(DeleteArgs),
 
     // =========================================================================
     // Discovery & Query Commands
@@ -177,22 +182,26 @@ pub enum Commands {
     /// Preview content metadata (free).
     ///
     /// Shows title, price, L1 summary without paying.
-    Preview {
-        /// Hash of the content to preview.
-        hash: String,
-    },
+    Preview(PreviewArgs),
 
     /// Query content (paid).
     ///
     /// Retrieves full content and pays the owner.
-    Query {
-        /// Hash of the content to query.
-        hash: String,
+    Query(QueryArgs),
 
-        /// Output path for the content (optional).
-        #[arg(short, long)]
-        output: Option<PathBuf>,
-    },
+    /// Show the content we bought.
+    ///
+    /// Lists paid queries, most recent first, with the price paid and the
+    /// provider. Bought content can be re-opened from the cache without
+    /// paying again, or re-downloaded from its provider once evicted.
+    Library(LibraryArgs),
+
+    /// Report how queried content was used back to its publisher.
+    ///
+    /// Sends an anonymized usage report (bytes read, model context size,
+    /// outcome rating). Requires usage_reports.send in the config; the
+    /// publisher only records it if it accepts usage reports.
+    ReportUsage(ReportUsageArgs),
 
     // =========================================================================
     // Synthesis Commands
@@ -200,62 +209,38 @@ pub enum Commands {
     /// Create L3 synthesis from sources.
     ///
     /// Combines insights from multiple sources with proper provenance.
-    Synthesize {
-        /// Source content hashes (comma-separated).
-        #[arg(short, long, value_delimiter = ',', required = true)]
-        sources: Vec<String>,
-
-        /// Path to the synthesis output file.
-        #[arg(short, long)]
-        output: PathBuf,
-
-        /// Title for the synthesis.
-        #[arg(short, long)]
-        title: Option<String>,
-
-        /// Price if publishing (optional).
-        #[arg(short, long, allow_hyphen_values = true, value_parser = parse_non_negative_price)]
-        price: Option<f64>,
-
-        /// Publish immediately after creation.
-        #[arg(long)]
-        publish: bool,
-    },
+    Synthesize(SynthesizeArgs),
 
     /// Build L2 Entity Graph from L1 sources.
     ///
     /// Creates a personal knowledge graph from extracted mentions.
-    BuildL2 {
-        /// L1 content hashes to include.
-        #[arg(required = true)]
-        sources: Vec<String>,
-
-        /// Title for the entity graph.
-        #[arg(short, long)]
-        title: Option<String>,
-    },
+    BuildL2(BuildL2Args),
 
     /// Merge multiple L2 Entity Graphs.
     ///
     /// Combines entity graphs with conflict resolution.
-    MergeL2 {
-        /// L2 graph hashes to merge.
-        #[arg(required = true)]
-        graphs: Vec<String>,
-
-        /// Title for the merged graph.
-        #[arg(short, long)]
-        title: Option<String>,
-    },
+    MergeL2(MergeL2Args),
 
     /// Reference external L3 as L0 for future derivations.
     ///
     /// Promotes an L3 synthesis to a primary source, allowing it
     /// to be used as a foundation for new content.
-    Reference {
-        /// Hash of the L3 content to reference.
-        hash: String,
-    },
+    Reference(ReferenceArgs),
+
+    /// Issue a signed attribution certificate for your L3 content.
+    ///
+    /// The certificate lists the provenance chain, source weights and
+    /// on-chain attestations as JSON-LD, for attaching to outputs built
+    /// from the content.
+    Attribution(AttributionArgs),
+
+    /// Verify an attribution certificate file.
+    VerifyAttribution(VerifyAttributionArgs),
+
+    /// Endorse an attribution certificate as the owner of one of its sources.
+    ///
+    /// Adds your signature to the certificate file.
+    EndorseAttribution(EndorseAttributionArgs),
 
     // =========================================================================
     // Economics Commands
@@ -266,33 +251,47 @@ pub enum Commands {
     /// Show earnings breakdown by content.
     ///
     /// Lists content sorted by total revenue earned.
-    Earnings {
-        /// Filter by content hash prefix.
-        #[arg(long)]
-        content: Option<String>,
+    Earnings(EarningsArgs),
 
-        /// Maximum results to show.
-        #[arg(short, long, default_value = "10")]
-        limit: u32,
-    },
+    /// Show access statistics and reported usage for your content.
+    ///
+    /// Includes previews, queries, unique consumers, revenue, and the usage
+    /// reports consumers sent back (bytes read, ratings, context sizes).
+    Stats(StatsArgs),
+
+    /// Show the double-entry economic ledger.
+    ///
+    /// Lists recent entries with running balances, every account balance,
+    /// and a reconciliation against open channels and the settlement queue.
+    Ledger(LedgerArgs),
 
     /// Deposit tokens to protocol balance.
-    Deposit {
-        /// Amount in HBAR to deposit.
-        amount: f64,
-    },
+    Deposit(DepositArgs),
 
     /// Withdraw tokens from protocol balance.
-    Withdraw {
-        /// Amount in HBAR to withdraw.
-        amount: f64,
-    },
+    Withdraw(WithdrawArgs),
+
+    /// Bond tokens from protocol balance as a publisher.
+    ///
+    /// The bond is recorded on published content so buyers can check it
+    /// before paying, and can be slashed if bad content is served.
+    PostBond(PostBondArgs),
+
+    /// Show our publisher bond.
+    Bond,
 
     /// Force settlement of pending payments.
     ///
     /// Creates a batch and settles on-chain.
     Settle,
 
+    /// Simulate revenue distribution over randomized payments.
+    ///
+    /// Replays payments across a synthetic provenance graph, checks value
+    /// conservation, fairness, and settlement batches, and reports how the
+    /// revenue was split. Runs entirely offline.
+    Simulate(SimulateArgs),
+
     // =========================================================================
     // Channel Commands
     // =========================================================================
@@ -302,155 +301,1534 @@ pub enum Commands {
     /// The deposit is locked until the channel is closed.
     ///
     /// Minimum deposit: 100 HBAR
-    OpenChannel {
-        /// Peer ID to open channel with (ndl1..., 12D3KooW..., or 40 hex chars).
-        peer_id: String,
-
-        /// Deposit amount in HBAR (minimum: 100).
-        #[arg(short, long)]
-        deposit: f64,
-    },
+    OpenChannel(OpenChannelArgs),
 
     /// Close a payment channel with a peer.
     ///
     /// Attempts cooperative close first (requires peer to be online).
     /// If the peer doesn't respond, suggests using dispute-channel.
-    CloseChannel {
-        /// Peer ID of the channel to close.
-        peer_id: String,
-    },
+    CloseChannel(CloseChannelArgs),
 
     /// Initiate a dispute-based channel close.
     ///
     /// Use this when the peer is offline or unresponsive.
     /// Starts a 24-hour dispute period before funds can be released.
-    DisputeChannel {
-        /// Peer ID of the channel to dispute.
-        peer_id: String,
-    },
+    DisputeChannel(DisputeChannelArgs),
 
     /// Resolve a channel dispute after the waiting period.
     ///
     /// Can only be called after the 24-hour dispute period has elapsed.
     /// Finalizes the channel close and distributes funds.
-    ResolveDispute {
-        /// Peer ID of the disputed channel.
-        peer_id: String,
-    },
+    ResolveDispute(ResolveDisputeArgs),
 
     /// List all payment channels.
     ///
     /// Shows all channels with their states and balances.
     ListChannels,
 
+    /// Rebalance skewed payment channels.
+    ///
+    /// Shows which open channels are saturated or depleted and the plan to
+    /// even them out: circular payments (suggested only) and close/reopen of
+    /// depleted channels (performed unless --dry-run is given).
+    RebalanceChannels(RebalanceChannelsArgs),
+
+    // =========================================================================
+    // Invoice Commands
+    // =========================================================================
+    /// Create a signed invoice for a payment to you.
+    ///
+    /// Share the invoice hash with the payer, or send it with send-invoice.
+    CreateInvoice(CreateInvoiceArgs),
+
+    /// List issued and received invoices.
+    ListInvoices(ListInvoicesArgs),
+
+    /// Send one of your invoices to the peer who should pay it.
+    SendInvoice(SendInvoiceArgs),
+
+    /// Fetch an invoice from its payee by hash.
+    FetchInvoice(FetchInvoiceArgs),
+
+    /// Pay a received invoice.
+    ///
+    /// Pays through the payment channel with the payee, which must be open
+    /// with enough balance.
+    PayInvoice(PayInvoiceArgs),
+
+    // =========================================================================
+    // Group Commands
+    // =========================================================================
+    /// Create a signed group membership list.
+    ///
+    /// Members of the group can query unlisted content that names the group
+    /// with `visibility --group`.
+    CreateGroup(CreateGroupArgs),
+
+    /// Add or remove members of one of your groups.
+    UpdateGroup(UpdateGroupArgs),
+
+    /// List groups you own or have cached.
+    ListGroups,
+
+    /// Fetch another peer's group so your content can reference it.
+    FetchGroup(FetchGroupArgs),
+
+    // =========================================================================
+    // Moderation Commands
+    // =========================================================================
+    /// Report content to the network for moderation.
+    ///
+    /// Signs and broadcasts a report; each node decides what to do with it
+    /// according to its `[moderation]` policy.
+    Report(ReportArgs),
+
+    /// List reported content awaiting review.
+    ///
+    /// Shows content with no decision yet and content hidden automatically.
+    ModerationQueue(ModerationQueueArgs),
+
+    /// Show the reports received for a piece of content.
+    Reports(ReportsArgs),
+
+    /// Hide content from search results on this node.
+    Hide(HideArgs),
+
+    /// Keep reported content visible, dismissing its reports.
+    Allow(AllowArgs),
+
     // =========================================================================
     // Node Management Commands
     // =========================================================================
     /// Start the Nodalync node.
     ///
     /// Begins listening for connections and serving content.
-    Start {
-        /// Run in daemon mode (background).
-        #[arg(short, long)]
-        daemon: bool,
+    Start(StartArgs),
 
-        /// Enable HTTP health endpoint.
-        #[arg(long)]
-        health: bool,
+    /// Show node status.
+    ///
+    /// Displays uptime, peers, content counts, pending payments.
+    Status,
+
+    /// Stop the running node.
+    ///
+    /// Gracefully shuts down the node.
+    Stop,
+
+    /// Check node integrity.
+    ///
+    /// Shows the result of the last content scrub and any quarantined
+    /// content. With --scrub, re-hashes stored content now.
+    Doctor(DoctorArgs),
+
+    /// Data retention policy.
+    ///
+    /// Limits are set per category under [retention] in config.toml and
+    /// enforced by a running node.
+    Retention(RetentionArgs),
+
+    /// Event webhooks.
+    ///
+    /// Endpoints are registered under [ops.webhooks] in config.toml and
+    /// notified by a running node.
+    Webhooks(WebhooksArgs),
+
+    /// Standing orders buying announced content automatically.
+    ///
+    /// A running node buys matching content when `enabled` is set under
+    /// [standing_orders] in config.toml, writing it to the inbox folder.
+    StandingOrders(StandingOrdersArgs),
+
+    /// Tenants: end-users served from this node, e.g. by a hosted gateway.
+    ///
+    /// Tenants share the node's identity, network and content cache. Each
+    /// has its own API key and budget; its queries are tagged with it and
+    /// earnings from content attributed to it are reported separately.
+    Tenants(TenantsArgs),
+
+    /// Show the digest of earnings and activity for the last full period.
+    ///
+    /// A running node sends these when `daily` or `weekly` is set under
+    /// [notifications] in config.toml.
+    Digest(DigestArgs),
+
+    /// Move identity and channel state between devices.
+    ///
+    /// Bundles are encrypted under the identity password. Move them as a
+    /// file, or leave them with a peer that holds sync bundles
+    /// (`sync.serve` under [ops]) for your other devices to pull.
+    Sync(SyncArgs),
+
+    /// Serve another publisher's catalog as a read-only replica.
+    ///
+    /// The primary exports its catalog with a signed delegation naming the
+    /// replica; the replica imports it and announces the content on the
+    /// primary's behalf. Queries for it still pay the primary.
+    Replica(ReplicaArgs),
+
+    /// Show node logs.
+    ///
+    /// Reads the JSON log files written by a running node.
+    Logs(LogsArgs),
+
+    /// Replay a recorded wire message log.
+    ///
+    /// Feeds the messages in a replay log (written with `replay_log = true`
+    /// under [network]) back through the handlers against fresh in-memory
+    /// state, at the times they were received. Nothing is sent.
+    Replay(ReplayArgs),
+
+    /// Network diagnostics.
+    Net(NetArgs),
+
+    /// Known peers.
+    Peers(PeersArgs),
+
+    /// Show whether our shared content is fetchable from outside.
+    ///
+    /// Lists the latest availability check of each hash made by a running
+    /// node (see [availability]), failing content first.
+    Availability(AvailabilityArgs),
+
+    // =========================================================================
+    // MCP Server Commands
+    // =========================================================================
+    /// Start the MCP server for AI assistant integration.
+    ///
+    /// Runs an MCP server on stdio that allows AI assistants like Claude
+    /// to query knowledge from your local node.
+    McpServer(McpServerArgs),
+
+    /// Start a gRPC server exposing node operations.
+    ///
+    /// Serves the `nodalync.v1.Operations` service so services in other
+    /// languages can publish, query and manage channels through generated
+    /// clients.
+    GrpcServer(GrpcServerArgs),
+
+    // =========================================================================
+    // Discovery Commands
+    // =========================================================================
+    /// Search for content by keyword.
+    ///
+    /// Searches title, description, and tags of local content.
+    Search(SearchArgs),
+
+    /// List tags, or suggest tags for a prefix.
+    ///
+    /// Tags are hierarchical (e.g. "science/biology"); counts include
+    /// content filed under child tags.
+    Tags(TagsArgs),
+
+    /// Browse a publisher's storefront, or preview and publish your own.
+    ///
+    /// A storefront is the signed catalog a publisher keeps in the DHT: its
+    /// content with categories, prices and featured items. Without a
+    /// publisher, shows the storefront your shared content makes; a running
+    /// node with `[storefront] enabled` republishes it periodically.
+    Storefront(StorefrontArgs),
+
+    // =========================================================================
+    // Shell Completion Commands
+    // =========================================================================
+    /// Generate shell completions.
+    ///
+    /// Outputs shell completion scripts for various shells.
+    Completions(CompletionsArgs),
+}
+
+/// Arguments of [`Commands::Init`].
+#[derive(Args, Debug)]
+pub struct InitArgs {
+    /// Run interactive setup wizard.
+    #[arg(short, long)]
+    pub wizard: bool,
+}
+
+/// Arguments of [`Commands::Did`].
+#[derive(Args, Debug)]
+pub struct DidArgs {
+    /// Write the DID document to this file.
+    #[arg(long, value_name = "FILE")]
+    pub export: Option<PathBuf>,
+}
+
+/// Arguments of [`Commands::ResolveDid`].
+#[derive(Args, Debug)]
+pub struct ResolveDidArgs {
+    /// The DID (did:key:z6Mk...).
+    pub did: String,
+}
+
+/// Arguments of [`Commands::VerifyDid`].
+#[derive(Args, Debug)]
+pub struct VerifyDidArgs {
+    /// DID document file (JSON).
+    pub file: PathBuf,
+
+    /// Require the document to belong to this peer.
+    #[arg(long)]
+    pub peer: Option<String>,
+}
+
+/// Arguments of [`Commands::Revoke`].
+#[derive(Args, Debug)]
+pub struct RevokeArgs {
+    /// Revocation certificate file (JSON).
+    pub certificate: PathBuf,
+}
+
+/// Arguments of [`Commands::Publish`].
+#[derive(Args, Debug)]
+pub struct PublishArgs {
+    /// Path to the file to publish.
+    pub file: PathBuf,
+
+    /// Price per query in HBAR (default from config).
+    #[arg(short, long, allow_hyphen_values = true, value_parser = parse_non_negative_price)]
+    pub price: Option<f64>,
+
+    /// Visibility level.
+    #[arg(short = 'V', long, default_value = "shared")]
+    pub visibility: VisibilityArg,
+
+    /// Title for the content (defaults to filename).
+    #[arg(short, long)]
+    pub title: Option<String>,
+
+    /// Description for the content.
+    #[arg(short, long)]
+    pub description: Option<String>,
+
+    /// Publish at a later time (RFC 3339, e.g. "2025-07-01T09:00Z").
+    ///
+    /// The content is stored now but is not announced or served until
+    /// then. A running node announces it at that time.
+    #[arg(long, value_parser = parse_publish_time)]
+    pub at: Option<u64>,
+
+    /// Schema URI for structured metadata fields
+    /// (e.g. "nodalync:schema/citation/v1").
+    #[arg(long)]
+    pub schema: Option<String>,
+
+    /// Structured metadata fields as a JSON object, validated against
+    /// the schema.
+    #[arg(long, requires = "schema")]
+    pub fields: Option<String>,
+
+    /// Tag for the content; repeat for several. Tags are hierarchical
+    /// (e.g. "science/biology"). See `nodalync tags` for existing tags.
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Suggest a price range from similar content on the network and
+    /// demand for your own, without publishing.
+    #[arg(long, conflicts_with_all = ["price", "at"])]
+    pub suggest_price: bool,
+}
+
+/// Arguments of [`Commands::Import`].
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    #[command(subcommand)]
+    pub command: ImportCommands,
+}
+
+/// Arguments of [`Commands::List`].
+#[derive(Args, Debug)]
+pub struct ListArgs {
+    /// Filter by visibility level.
+    #[arg(short = 'V', long)]
+    pub visibility: Option<VisibilityArg>,
+
+    /// Filter by content type.
+    #[arg(short = 't', long)]
+    pub content_type: Option<ContentTypeArg>,
+
+    /// Maximum results to show.
+    #[arg(short, long, default_value = "50")]
+    pub limit: u32,
+
+    /// Include content available from network peers.
+    #[arg(short = 'n', long)]
+    pub network: bool,
+}
+
+/// Arguments of [`Commands::Update`].
+#[derive(Args, Debug)]
+pub struct UpdateArgs {
+    /// Hash of the content to update.
+    pub hash: String,
+
+    /// Path to the new file.
+    pub file: PathBuf,
+
+    /// New title (optional).
+    #[arg(short, long)]
+    pub title: Option<String>,
+
+    /// Price per query in HBAR (defaults to previous version's price).
+    #[arg(short, long, allow_hyphen_values = true, value_parser = parse_non_negative_price)]
+    pub price: Option<f64>,
+}
+
+/// Arguments of [`Commands::Visibility`].
+#[derive(Args, Debug)]
+pub struct VisibilityArgs {
+    /// Hash of the content.
+    pub hash: String,
+
+    /// New visibility level (private, unlisted, or shared).
+    #[arg(short, long, alias = "visibility")]
+    pub level: VisibilityArg,
+
+    /// Admit members of this group to unlisted content (repeatable).
+    #[arg(long = "group")]
+    pub groups: Vec<String>,
+}
+
+/// Arguments of [`Commands::PreviewPolicy`].
+#[derive(Args, Debug)]
+pub struct PreviewPolicyArgs {
+    /// Hash of the content.
+    pub hash: String,
+
+    /// Replace text matching this regular expression (repeatable).
+    #[arg(long = "redact")]
+    pub patterns: Vec<String>,
+
+    /// Leave out the Markdown section with this heading (repeatable).
+    #[arg(long = "redact-section")]
+    pub sections: Vec<String>,
+
+    /// Remove the preview policy.
+    #[arg(long, conflicts_with_all = ["patterns", "sections"])]
+    pub clear: bool,
+}
+
+/// Arguments of [`Commands::Pricing`].
+#[derive(Args, Debug)]
+pub struct PricingArgs {
+    /// Hash of the content.
+    pub hash: String,
+
+    /// Surge pricing: highest price in HBAR.
+    #[arg(long, value_parser = parse_non_negative_price, requires = "surge_step")]
+    pub surge_max: Option<f64>,
+
+    /// Surge pricing: increase per paid query in the previous window,
+    /// in basis points of the base price.
+    #[arg(long, requires = "surge_max")]
+    pub surge_step: Option<u32>,
+
+    /// Surge pricing: window length in minutes (default: 60).
+    #[arg(long, requires = "surge_max")]
+    pub window: Option<u64>,
+
+    /// Dutch auction starting now: starting price in HBAR.
+    #[arg(
+        long,
+        value_parser = parse_non_negative_price,
+        requires = "decay_hours",
+        conflicts_with = "surge_max"
+    )]
+    pub decay_from: Option<f64>,
+
+    /// Dutch auction: hours until the price is back at the floor.
+    #[arg(long, requires = "decay_from")]
+    pub decay_hours: Option<f64>,
+
+    /// Remove the pricing rule and charge the content's price.
+    #[arg(long, conflicts_with_all = ["surge_max", "decay_from"])]
+    pub fixed: bool,
+}
+
+/// Arguments of [`Commands::Sections`].
+#[derive(Args, Debug)]
+pub struct SectionsArgs {
+    /// Hash of the content.
+    pub hash: String,
+
+    /// Price a section as HEADING=HBAR (repeatable).
+    #[arg(long = "price", value_parser = parse_section_price)]
+    pub prices: Vec<(String, f64)>,
+
+    /// Remove all sections.
+    #[arg(long, conflicts_with = "prices")]
+    pub clear: bool,
+}
+
+/// Arguments of [`Commands::Metering`].
+#[derive(Args, Debug)]
+pub struct MeteringArgs {
+    /// Hash of the content.
+    pub hash: String,
+
+    /// Meter the content in units of this many KiB.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub unit_kb: Option<u64>,
+
+    /// Stop metering the content.
+    #[arg(long, conflicts_with = "unit_kb")]
+    pub off: bool,
+}
+
+/// Arguments of [`Commands::Trial`].
+#[derive(Args, Debug)]
+pub struct TrialArgs {
+    /// Hash of the content.
+    pub hash: String,
+
+    /// Free reads per peer.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub reads: Option<u32>,
+
+    /// Make only the first this many bytes free.
+    #[arg(long, requires = "reads", value_parser = clap::value_parser!(u64).range(1..))]
+    pub bytes: Option<u64>,
 
-        /// Port for the HTTP health endpoint.
-        #[arg(long, default_value = "8080")]
-        health_port: u16,
+    /// Stop offering a trial.
+    #[arg(long, conflicts_with = "reads")]
+    pub off: bool,
+}
+
+/// Arguments of [`Commands::Quota`].
+#[derive(Args, Debug)]
+pub struct QuotaArgs {
+    /// Hash of the content.
+    pub hash: String,
+
+    /// Queries per peer per day.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub queries: Option<u32>,
+
+    /// Bytes served to each peer per day.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub bytes: Option<u64>,
+
+    /// Lift the quota.
+    #[arg(long, conflicts_with_all = ["queries", "bytes"])]
+    pub off: bool,
+
+    /// Forget this peer's usage today, giving it its full quotas again.
+    #[arg(long, value_name = "PEER_ID")]
+    pub reset: Option<String>,
+}
+
+/// Arguments of [`Commands::Referral`].
+#[derive(Args, Debug)]
+pub struct ReferralArgs {
+    /// Hash of the content.
+    pub hash: String,
+
+    /// Referral cut, as a percentage of each payment.
+    #[arg(long)]
+    pub percent: Option<f64>,
+
+    /// Stop offering a referral cut.
+    #[arg(long, conflicts_with = "percent")]
+    pub off: bool,
+}
+
+/// Arguments of [`Commands::Share`].
+#[derive(Args, Debug)]
+pub struct ShareArgs {
+    /// Hash of the content.
+    pub hash: String,
+
+    /// Only this peer may use the token.
+    #[arg(long)]
+    pub peer: Option<String>,
+
+    /// Hours until the token expires (default 24).
+    #[arg(long)]
+    pub expires_in: Option<u64>,
+}
+
+/// Arguments of [`Commands::Versions`].
+#[derive(Args, Debug)]
+pub struct VersionsArgs {
+    /// Hash of any version in the chain.
+    pub hash: String,
+
+    /// Fetch missing version manifests from the owner.
+    #[arg(long)]
+    pub repair: bool,
+}
+
+/// Arguments of [`Commands::Delete`].
+#[derive(Args, Debug)]
+pub struct DeleteArgs {
+    /// Hash of the content to delete.
+    pub hash: String,
+
+    /// Skip confirmation prompt.
+    #[arg(short = 'F', long)]
+    pub force: bool,
+}
+
+/// Arguments of [`Commands::Preview`].
+#[derive(Args, Debug)]
+pub struct PreviewArgs {
+    /// Hash of the content to preview.
+    pub hash: String,
+}
+
+/// Arguments of [`Commands::Query`].
+#[derive(Args, Debug)]
+pub struct QueryArgs {
+    /// Hash of the content to query.
+    pub hash: String,
+
+    /// Output path for the content (optional).
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Capability token from `share`, for content that is not published.
+    #[arg(long)]
+    pub capability: Option<String>,
+
+    /// Buy only this section (see `sections`), at its own price.
+    #[arg(long, conflicts_with = "capability")]
+    pub section: Option<u32>,
+
+    /// Read metered content (see `metering`) unit by unit, paying per unit.
+    #[arg(long, conflicts_with_all = ["capability", "section"])]
+    pub metered: bool,
+
+    /// With --metered, stop once the content read contains this text.
+    #[arg(long, requires = "metered")]
+    pub until: Option<String>,
+
+    /// With --metered, stop once this many bytes are read.
+    #[arg(long, requires = "metered")]
+    pub max_bytes: Option<u64>,
+
+    /// Take a free trial read (see `trial`) instead of paying.
+    #[arg(long, conflicts_with_all = ["capability", "section", "metered"])]
+    pub trial: bool,
+
+    /// Peer that referred this query, paid the content's referral cut
+    /// (see `referral`).
+    #[arg(long, conflicts_with = "trial")]
+    pub referrer: Option<String>,
+}
+
+/// Arguments of [`Commands::Library`].
+#[derive(Args, Debug)]
+pub struct LibraryArgs {
+    /// Only show purchases whose title contains this text.
+    pub search: Option<String>,
+
+    /// Re-open this bought content from the cache, re-downloading it
+    /// with the purchase receipt if it was evicted.
+    #[arg(long, value_name = "HASH", conflicts_with = "search")]
+    pub open: Option<String>,
+
+    /// With --open, write the content to this file.
+    #[arg(short, long, requires = "open")]
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments of [`Commands::ReportUsage`].
+#[derive(Args, Debug)]
+pub struct ReportUsageArgs {
+    /// Hash of the queried content.
+    pub hash: String,
+
+    /// Bytes of the content that were actually read.
+    #[arg(long)]
+    pub bytes_read: u64,
+
+    /// Size of the model context the content was used in, in tokens.
+    #[arg(long)]
+    pub context_tokens: Option<u32>,
+
+    /// How useful the content was, from 1 to 5.
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=5))]
+    pub rating: Option<u8>,
+}
+
+/// Arguments of [`Commands::Synthesize`].
+#[derive(Args, Debug)]
+pub struct SynthesizeArgs {
+    /// Source content hashes (comma-separated).
+    #[arg(short, long, value_delimiter = ',', required = true)]
+    pub sources: Vec<String>,
+
+    /// Path to the synthesis output file.
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// Title for the synthesis.
+    #[arg(short, long)]
+    pub title: Option<String>,
+
+    /// Price if publishing (optional).
+    #[arg(short, long, allow_hyphen_values = true, value_parser = parse_non_negative_price)]
+    pub price: Option<f64>,
+
+    /// Publish immediately after creation.
+    #[arg(long)]
+    pub publish: bool,
+}
+
+/// Arguments of [`Commands::BuildL2`].
+#[derive(Args, Debug)]
+pub struct BuildL2Args {
+    /// L1 content hashes to include.
+    #[arg(required = true)]
+    pub sources: Vec<String>,
+
+    /// Title for the entity graph.
+    #[arg(short, long)]
+    pub title: Option<String>,
+}
+
+/// Arguments of [`Commands::MergeL2`].
+#[derive(Args, Debug)]
+pub struct MergeL2Args {
+    /// L2 graph hashes to merge.
+    #[arg(required = true)]
+    pub graphs: Vec<String>,
+
+    /// Title for the merged graph.
+    #[arg(short, long)]
+    pub title: Option<String>,
+}
+
+/// Arguments of [`Commands::Reference`].
+#[derive(Args, Debug)]
+pub struct ReferenceArgs {
+    /// Hash of the L3 content to reference.
+    pub hash: String,
+}
+
+/// Arguments of [`Commands::Attribution`].
+#[derive(Args, Debug)]
+pub struct AttributionArgs {
+    /// Hash of the L3 content.
+    pub hash: String,
+
+    /// Write the certificate to this file.
+    #[arg(long, value_name = "FILE")]
+    pub export: Option<PathBuf>,
+}
+
+/// Arguments of [`Commands::VerifyAttribution`].
+#[derive(Args, Debug)]
+pub struct VerifyAttributionArgs {
+    /// Certificate file (JSON-LD).
+    pub file: PathBuf,
+}
+
+/// Arguments of [`Commands::EndorseAttribution`].
+#[derive(Args, Debug)]
+pub struct EndorseAttributionArgs {
+    /// Certificate file (JSON-LD).
+    pub file: PathBuf,
+}
+
+/// Arguments of [`Commands::Earnings`].
+#[derive(Args, Debug)]
+pub struct EarningsArgs {
+    /// Filter by content hash prefix.
+    #[arg(long)]
+    pub content: Option<String>,
+
+    /// Maximum results to show.
+    #[arg(short, long, default_value = "10")]
+    pub limit: u32,
+}
+
+/// Arguments of [`Commands::Stats`].
+#[derive(Args, Debug)]
+pub struct StatsArgs {
+    /// Hash of the content.
+    pub hash: String,
+}
+
+/// Arguments of [`Commands::Ledger`].
+#[derive(Args, Debug)]
+pub struct LedgerArgs {
+    /// Only show entries for this account (external, contract, channels,
+    /// payable, revenue, fees, query_spend).
+    #[arg(long, value_parser = parse_ledger_account)]
+    pub account: Option<LedgerAccount>,
+
+    /// Only entries from this time onwards (RFC 3339).
+    #[arg(long, value_parser = parse_publish_time)]
+    pub since: Option<u64>,
+
+    /// Maximum entries to show (most recent).
+    #[arg(short, long, default_value = "20")]
+    pub limit: u32,
+
+    /// Write entries to a CSV file for accounting instead.
+    #[arg(long, value_name = "FILE")]
+    pub export: Option<PathBuf>,
+}
+
+/// Arguments of [`Commands::Deposit`].
+#[derive(Args, Debug)]
+pub struct DepositArgs {
+    /// Amount in HBAR to deposit.
+    pub amount: f64,
+}
+
+/// Arguments of [`Commands::Withdraw`].
+#[derive(Args, Debug)]
+pub struct WithdrawArgs {
+    /// Amount in HBAR to withdraw.
+    pub amount: f64,
+}
+
+/// Arguments of [`Commands::PostBond`].
+#[derive(Args, Debug)]
+pub struct PostBondArgs {
+    /// Amount in HBAR to bond.
+    pub amount: f64,
+}
+
+/// Arguments of [`Commands::Simulate`].
+#[derive(Args, Debug)]
+pub struct SimulateArgs {
+    /// Seed for the random generator (same seed, same report).
+    #[arg(long, default_value = "0")]
+    pub seed: u64,
+
+    /// Number of payments to replay.
+    #[arg(short = 'n', long, default_value = "10000")]
+    pub payments: usize,
+
+    /// Number of contributing peers.
+    #[arg(long, default_value = "50")]
+    pub contributors: usize,
+
+    /// Number of source (L0) content items.
+    #[arg(long, default_value = "200")]
+    pub sources: usize,
+
+    /// Number of derived (L3) content items.
+    #[arg(long, default_value = "100")]
+    pub derived: usize,
+
+    /// Maximum inputs per derived item.
+    #[arg(long, default_value = "5")]
+    pub max_inputs: usize,
+
+    /// Largest payment in HBAR (payments range from 1 tinybar up to this).
+    #[arg(long, default_value = "1.0", value_parser = parse_non_negative_price)]
+    pub max_price: f64,
+
+    /// Payments per settlement batch.
+    #[arg(long, default_value = "100")]
+    pub batch_size: usize,
+
+    /// Number of top recipients to show.
+    #[arg(long, default_value = "10")]
+    pub top: usize,
+}
+
+/// Arguments of [`Commands::OpenChannel`].
+#[derive(Args, Debug)]
+pub struct OpenChannelArgs {
+    /// Peer ID to open channel with (ndl1..., 12D3KooW..., or 40 hex chars).
+    pub peer_id: String,
+
+    /// Deposit amount in HBAR (minimum: 100).
+    #[arg(short, long)]
+    pub deposit: f64,
+}
+
+/// Arguments of [`Commands::CloseChannel`].
+#[derive(Args, Debug)]
+pub struct CloseChannelArgs {
+    /// Peer ID of the channel to close.
+    pub peer_id: String,
+}
+
+/// Arguments of [`Commands::DisputeChannel`].
+#[derive(Args, Debug)]
+pub struct DisputeChannelArgs {
+    /// Peer ID of the channel to dispute.
+    pub peer_id: String,
+}
+
+/// Arguments of [`Commands::ResolveDispute`].
+#[derive(Args, Debug)]
+pub struct ResolveDisputeArgs {
+    /// Peer ID of the disputed channel.
+    pub peer_id: String,
+}
+
+/// Arguments of [`Commands::RebalanceChannels`].
+#[derive(Args, Debug)]
+pub struct RebalanceChannelsArgs {
+    /// Only show the plan, don't close or reopen any channel.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Arguments of [`Commands::CreateInvoice`].
+#[derive(Args, Debug)]
+pub struct CreateInvoiceArgs {
+    /// Amount in HBAR.
+    pub amount: f64,
+
+    /// What the invoice is for.
+    #[arg(short, long)]
+    pub memo: Option<String>,
+
+    /// Hash of the content or deliverable the invoice refers to.
+    #[arg(long)]
+    pub reference: Option<String>,
+
+    /// Hours until the invoice expires (default: 7 days).
+    #[arg(long)]
+    pub expires_in: Option<u64>,
+}
+
+/// Arguments of [`Commands::ListInvoices`].
+#[derive(Args, Debug)]
+pub struct ListInvoicesArgs {
+    /// Only show invoices in one direction (issued, received).
+    #[arg(long, value_parser = parse_invoice_direction)]
+    pub direction: Option<InvoiceDirection>,
+
+    /// Only show unpaid invoices.
+    #[arg(long)]
+    pub unpaid: bool,
+}
+
+/// Arguments of [`Commands::SendInvoice`].
+#[derive(Args, Debug)]
+pub struct SendInvoiceArgs {
+    /// Invoice hash.
+    pub hash: String,
+
+    /// Peer ID of the payer.
+    pub peer_id: String,
+}
+
+/// Arguments of [`Commands::FetchInvoice`].
+#[derive(Args, Debug)]
+pub struct FetchInvoiceArgs {
+    /// Peer ID of the payee.
+    pub peer_id: String,
+
+    /// Invoice hash.
+    pub hash: String,
+}
+
+/// Arguments of [`Commands::PayInvoice`].
+#[derive(Args, Debug)]
+pub struct PayInvoiceArgs {
+    /// Invoice hash.
+    pub hash: String,
+}
+
+/// Arguments of [`Commands::CreateGroup`].
+#[derive(Args, Debug)]
+pub struct CreateGroupArgs {
+    /// Group name, unique among your groups.
+    pub name: String,
+
+    /// Peer ID of a member (repeatable).
+    #[arg(long = "member")]
+    pub members: Vec<String>,
+}
+
+/// Arguments of [`Commands::UpdateGroup`].
+#[derive(Args, Debug)]
+pub struct UpdateGroupArgs {
+    /// Group ID.
+    pub id: String,
+
+    /// Peer ID to add (repeatable).
+    #[arg(long)]
+    pub add: Vec<String>,
+
+    /// Peer ID to remove (repeatable).
+    #[arg(long)]
+    pub remove: Vec<String>,
+}
+
+/// Arguments of [`Commands::FetchGroup`].
+#[derive(Args, Debug)]
+pub struct FetchGroupArgs {
+    /// Peer ID of the group owner.
+    pub owner: String,
+
+    /// Group ID.
+    pub id: String,
+}
+
+/// Arguments of [`Commands::Report`].
+#[derive(Args, Debug)]
+pub struct ReportArgs {
+    /// Hash of the content to report.
+    pub hash: String,
+
+    /// Why the content is being reported.
+    #[arg(long, value_enum)]
+    pub reason: ReportReasonArg,
+
+    /// Explanation for reviewers.
+    #[arg(long, default_value = "")]
+    pub comment: String,
+}
+
+/// Arguments of [`Commands::ModerationQueue`].
+#[derive(Args, Debug)]
+pub struct ModerationQueueArgs {
+    /// Include content that has already been hidden or allowed.
+    #[arg(long)]
+    pub all: bool,
+}
+
+/// Arguments of [`Commands::Reports`].
+#[derive(Args, Debug)]
+pub struct ReportsArgs {
+    /// Content hash.
+    pub hash: String,
+}
+
+/// Arguments of [`Commands::Hide`].
+#[derive(Args, Debug)]
+pub struct HideArgs {
+    /// Content hash.
+    pub hash: String,
+}
+
+/// Arguments of [`Commands::Allow`].
+#[derive(Args, Debug)]
+pub struct AllowArgs {
+    /// Content hash.
+    pub hash: String,
+}
+
+/// Arguments of [`Commands::Start`].
+#[derive(Args, Debug)]
+pub struct StartArgs {
+    /// Run in daemon mode (background).
+    #[arg(short, long)]
+    pub daemon: bool,
+
+    /// Enable HTTP health endpoint.
+    #[arg(long)]
+    pub health: bool,
+
+    /// Port for the HTTP health endpoint.
+    #[arg(long, default_value = "8080")]
+    pub health_port: u16,
+
+    /// Run as a bootstrap/relay node.
+    ///
+    /// Disables payments and content serving, applies per-IP rate
+    /// limits, and persists DHT records across restarts.
+    #[arg(long)]
+    pub bootstrap_mode: bool,
+}
+
+/// Arguments of [`Commands::Doctor`].
+#[derive(Args, Debug)]
+pub struct DoctorArgs {
+    /// Run a content scrub before reporting.
+    #[arg(long)]
+    pub scrub: bool,
+}
+
+/// Arguments of [`Commands::Retention`].
+#[derive(Args, Debug)]
+pub struct RetentionArgs {
+    #[command(subcommand)]
+    pub command: RetentionCommands,
+}
+
+/// Arguments of [`Commands::Webhooks`].
+#[derive(Args, Debug)]
+pub struct WebhooksArgs {
+    #[command(subcommand)]
+    pub command: WebhookCommands,
+}
+
+/// Arguments of [`Commands::StandingOrders`].
+#[derive(Args, Debug)]
+pub struct StandingOrdersArgs {
+    #[command(subcommand)]
+    pub command: StandingOrderCommands,
+}
+
+/// Arguments of [`Commands::Tenants`].
+#[derive(Args, Debug)]
+pub struct TenantsArgs {
+    #[command(subcommand)]
+    pub command: TenantCommands,
+}
+
+/// Arguments of [`Commands::Digest`].
+#[derive(Args, Debug)]
+pub struct DigestArgs {
+    /// Period to summarize (daily or weekly).
+    #[arg(long, default_value = "daily", value_parser = parse_digest_period)]
+    pub period: DigestPeriod,
+
+    /// Also send it through the notifiers configured under [notifications].
+    #[arg(long)]
+    pub send: bool,
+}
+
+/// Arguments of [`Commands::Sync`].
+#[derive(Args, Debug)]
+pub struct SyncArgs {
+    #[command(subcommand)]
+    pub command: SyncCommands,
+}
+
+/// Arguments of [`Commands::Replica`].
+#[derive(Args, Debug)]
+pub struct ReplicaArgs {
+    #[command(subcommand)]
+    pub command: ReplicaCommands,
+}
+
+/// Arguments of [`Commands::Logs`].
+#[derive(Args, Debug)]
+pub struct LogsArgs {
+    /// Keep printing new entries as they are written.
+    #[arg(short = 'F', long)]
+    pub follow: bool,
+
+    /// Minimum level to show (error, warn, info, debug, trace).
+    #[arg(short, long)]
+    pub level: Option<String>,
+
+    /// Only show entries newer than this (e.g. 30s, 15m, 1h, 2d).
+    #[arg(long)]
+    pub since: Option<String>,
+
+    /// Number of most recent entries to show.
+    #[arg(short = 'n', long, default_value = "100")]
+    pub lines: usize,
+}
+
+/// Arguments of [`Commands::Replay`].
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    /// Replay log file.
+    pub log: PathBuf,
+}
+
+/// Arguments of [`Commands::Net`].
+#[derive(Args, Debug)]
+pub struct NetArgs {
+    #[command(subcommand)]
+    pub command: NetCommands,
+}
+
+/// Arguments of [`Commands::Peers`].
+#[derive(Args, Debug)]
+pub struct PeersArgs {
+    #[command(subcommand)]
+    pub command: PeersCommands,
+}
+
+/// Arguments of [`Commands::Availability`].
+#[derive(Args, Debug)]
+pub struct AvailabilityArgs {
+    /// Only show content that failed its latest check.
+    #[arg(long)]
+    pub failing: bool,
+}
+
+/// Arguments of [`Commands::McpServer`].
+#[derive(Args, Debug)]
+pub struct McpServerArgs {
+    /// Session budget in HBAR (default: 1.0).
+    #[arg(short, long, default_value = "1.0")]
+    pub budget: f64,
+
+    /// Auto-approve threshold in HBAR (default: 0.01).
+    /// Queries below this amount are approved automatically.
+    #[arg(short, long, default_value = "0.01")]
+    pub auto_approve: f64,
+
+    /// Enable network connectivity for live peer search.
+    ///
+    /// When enabled, the MCP server can search connected peers
+    /// in real-time using the search_network tool.
+    #[arg(long)]
+    pub enable_network: bool,
+
+    /// Hedera account ID for settlement (e.g., 0.0.7703962).
+    #[arg(long, env = "NODALYNC_HEDERA_ACCOUNT_ID")]
+    pub hedera_account_id: Option<String>,
+
+    /// Path to Hedera private key file.
+    #[arg(long, env = "NODALYNC_HEDERA_KEY_PATH")]
+    pub hedera_private_key: Option<PathBuf>,
+
+    /// Hedera settlement contract ID (default: 0.0.7729011).
+    #[arg(
+        long,
+        env = "NODALYNC_HEDERA_CONTRACT_ID",
+        default_value = "0.0.7729011"
+    )]
+    pub hedera_contract_id: String,
+
+    /// Hedera network (testnet, mainnet, previewnet).
+    #[arg(long, env = "NODALYNC_HEDERA_NETWORK", default_value = "testnet")]
+    pub hedera_network: String,
+
+    /// API key of the tenant to serve (see `nodalync tenants`).
+    ///
+    /// Queries are also charged to the tenant's budget and tagged with
+    /// the tenant.
+    #[arg(long, env = "NODALYNC_TENANT_KEY", hide_env_values = true)]
+    pub tenant_key: Option<String>,
+}
+
+/// Arguments of [`Commands::GrpcServer`].
+#[derive(Args, Debug)]
+pub struct GrpcServerArgs {
+    /// Address to listen on.
+    #[arg(long, default_value = nodalync_grpc::DEFAULT_LISTEN_ADDR)]
+    pub listen: std::net::SocketAddr,
+}
+
+/// Arguments of [`Commands::Search`].
+#[derive(Args, Debug)]
+pub struct SearchArgs {
+    /// Search query (matches title, description, and tags).
+    pub query: String,
+
+    /// Filter by content type.
+    #[arg(short = 't', long)]
+    pub content_type: Option<ContentTypeArg>,
+
+    /// Maximum results to show.
+    #[arg(short, long, default_value = "20")]
+    pub limit: u32,
+
+    /// Search across network (not just local).
+    #[arg(short, long)]
+    pub all: bool,
+
+    /// Minimum price per query in HBAR.
+    #[arg(long, value_parser = parse_non_negative_price)]
+    pub min_price: Option<f64>,
+
+    /// Maximum price per query in HBAR.
+    #[arg(long, value_parser = parse_non_negative_price)]
+    pub max_price: Option<f64>,
+
+    /// Only content with this tag (repeat to require several).
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Only content from this owner (peer ID).
+    #[arg(long)]
+    pub owner: Option<String>,
+
+    /// Only content published after this time (RFC 3339).
+    #[arg(long, value_parser = parse_publish_time)]
+    pub after: Option<u64>,
+}
+
+/// Arguments of [`Commands::Tags`].
+#[derive(Args, Debug)]
+pub struct TagsArgs {
+    /// Partially typed tag to complete (matches the start of any
+    /// segment). Lists all tags if omitted.
+    pub prefix: Option<String>,
+
+    /// Maximum suggestions to show.
+    #[arg(short, long, default_value = "10")]
+    pub limit: u32,
+}
+
+/// Arguments of [`Commands::Storefront`].
+#[derive(Args, Debug)]
+pub struct StorefrontArgs {
+    /// Peer ID of the publisher. Your own storefront if omitted.
+    pub publisher: Option<String>,
+
+    /// Only show items in this category.
+    #[arg(long)]
+    pub category: Option<String>,
+
+    /// Store your storefront in the DHT now.
+    #[arg(long, conflicts_with = "publisher")]
+    pub publish: bool,
+}
+
+/// Arguments of [`Commands::Completions`].
+#[derive(Args, Debug)]
+pub struct CompletionsArgs {
+    /// Shell to generate completions for.
+    pub shell: CompletionShell,
+}
+
+/// Retention subcommands.
+#[derive(Subcommand, Debug)]
+pub enum RetentionCommands {
+    /// Show records held and expired in each category.
+    Status,
+}
+
+/// Webhook subcommands.
+#[derive(Subcommand, Debug)]
+pub enum WebhookCommands {
+    /// List recent webhook deliveries and their status.
+    List {
+        /// Only show deliveries with this status (pending, delivered, failed).
+        #[arg(long, value_parser = parse_webhook_status)]
+        status: Option<WebhookDeliveryStatus>,
+
+        /// Maximum deliveries to show (most recent).
+        #[arg(short, long, default_value = "20")]
+        limit: u32,
+    },
+}
+
+/// Standing order subcommands.
+#[derive(Subcommand, Debug)]
+pub enum StandingOrderCommands {
+    /// Add a standing order.
+    ///
+    /// Buys content announced from now on under the tag (or a child of
+    /// it), at or below the price.
+    Add {
+        /// Tag the content must be filed under (e.g. "science/biology").
+        #[arg(long)]
+        tag: String,
+
+        /// Highest price paid per item in HBAR.
+        #[arg(long, value_parser = parse_non_negative_price)]
+        max_price: f64,
+
+        /// Lowest publisher reputation accepted.
+        #[arg(long, allow_hyphen_values = true)]
+        min_reputation: Option<i64>,
+
+        /// Most spent by the order in total, in HBAR.
+        #[arg(long, value_parser = parse_non_negative_price)]
+        budget: Option<f64>,
+    },
+
+    /// List standing orders.
+    List,
+
+    /// Remove a standing order. What it bought is kept.
+    Remove {
+        /// Order ID.
+        id: u64,
+    },
+
+    /// List content bought by standing orders (most recent first).
+    Purchases {
+        /// Only purchases by this order.
+        #[arg(long)]
+        order: Option<u64>,
+
+        /// Maximum purchases to show.
+        #[arg(short, long, default_value = "20")]
+        limit: u32,
+    },
+}
+
+/// Tenant subcommands.
+#[derive(Subcommand, Debug)]
+pub enum TenantCommands {
+    /// Add a tenant and print its API key.
+    ///
+    /// The key is only shown once; use rotate-key to replace a lost one.
+    Create {
+        /// Tenant ID (lowercase letters, digits, '-' and '_').
+        id: String,
+
+        /// Display name (defaults to the ID).
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Most the tenant may spend in total, in HBAR.
+        #[arg(long, value_parser = parse_non_negative_price)]
+        budget: Option<f64>,
+    },
+
+    /// List tenants.
+    List,
+
+    /// Show a tenant's spending, content and earnings.
+    Show {
+        /// Tenant ID.
+        id: String,
+    },
+
+    /// Set a tenant's budget in HBAR, or remove it if omitted.
+    Budget {
+        /// Tenant ID.
+        id: String,
+
+        /// New budget in HBAR.
+        #[arg(value_parser = parse_non_negative_price)]
+        budget: Option<f64>,
+    },
+
+    /// Replace a tenant's API key. The old key stops working.
+    RotateKey {
+        /// Tenant ID.
+        id: String,
+    },
+
+    /// Remove a tenant. Its purchases are kept.
+    Remove {
+        /// Tenant ID.
+        id: String,
+    },
+
+    /// Attribute published content to a tenant, or clear it if omitted.
+    Assign {
+        /// Content hash.
+        hash: String,
+
+        /// Tenant ID.
+        tenant: Option<String>,
+    },
+
+    /// List queries paid for on behalf of tenants (most recent first).
+    Purchases {
+        /// Only purchases by this tenant.
+        #[arg(long)]
+        tenant: Option<String>,
+
+        /// Maximum purchases to show.
+        #[arg(short, long, default_value = "20")]
+        limit: u32,
+    },
+}
+
+/// Import subcommands.
+#[derive(Subcommand, Debug)]
+pub enum ImportCommands {
+    /// Import an IPFS CID.
+    ///
+    /// Fetches the content through the IPFS node API if one is configured
+    /// under [ipfs], or the gateway otherwise, and publishes it with the CID
+    /// recorded in its metadata (schema "nodalync:schema/ipfs/v1").
+    Ipfs {
+        /// CID to import (Qm... or bafy...).
+        cid: String,
+
+        /// Gateway to fetch from (default from config).
+        #[arg(long)]
+        gateway: Option<String>,
+
+        /// Price per query in HBAR (default from config).
+        #[arg(short, long, allow_hyphen_values = true, value_parser = parse_non_negative_price)]
+        price: Option<f64>,
+
+        /// Visibility level.
+        #[arg(short = 'V', long, default_value = "shared")]
+        visibility: VisibilityArg,
+
+        /// Title for the content (defaults to the CID).
+        #[arg(short, long)]
+        title: Option<String>,
+
+        /// Description for the content.
+        #[arg(short, long)]
+        description: Option<String>,
+
+        /// Tag for the content; repeat for several.
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+
+        /// Add a record linking the Nodalync hash to the CID to the IPFS
+        /// node, pinned. Needs the node API.
+        #[arg(long)]
+        pin: bool,
+    },
+}
+
+/// Sync subcommands.
+#[derive(Subcommand, Debug)]
+pub enum SyncCommands {
+    /// Export a sync bundle to a file.
+    Export {
+        /// File to write.
+        file: PathBuf,
     },
 
-    /// Show node status.
+    /// Import a sync bundle file.
     ///
-    /// Displays uptime, peers, content counts, pending payments.
-    Status,
+    /// On a device without an identity, installs the bundle's identity.
+    Import {
+        /// File to read.
+        file: PathBuf,
+    },
 
-    /// Stop the running node.
-    ///
-    /// Gracefully shuts down the node.
-    Stop,
+    /// Leave a sync bundle with a peer.
+    Push {
+        /// Peer holding the bundle (ndl1... or hex).
+        peer: String,
+    },
 
-    // =========================================================================
-    // MCP Server Commands
-    // =========================================================================
-    /// Start the MCP server for AI assistant integration.
-    ///
-    /// Runs an MCP server on stdio that allows AI assistants like Claude
-    /// to query knowledge from your local node.
-    McpServer {
-        /// Session budget in HBAR (default: 1.0).
-        #[arg(short, long, default_value = "1.0")]
-        budget: f64,
-
-        /// Auto-approve threshold in HBAR (default: 0.01).
-        /// Queries below this amount are approved automatically.
-        #[arg(short, long, default_value = "0.01")]
-        auto_approve: f64,
-
-        /// Enable network connectivity for live peer search.
-        ///
-        /// When enabled, the MCP server can search connected peers
-        /// in real-time using the search_network tool.
-        #[arg(long)]
-        enable_network: bool,
-
-        /// Hedera account ID for settlement (e.g., 0.0.7703962).
-        #[arg(long, env = "NODALYNC_HEDERA_ACCOUNT_ID")]
-        hedera_account_id: Option<String>,
-
-        /// Path to Hedera private key file.
-        #[arg(long, env = "NODALYNC_HEDERA_KEY_PATH")]
-        hedera_private_key: Option<PathBuf>,
-
-        /// Hedera settlement contract ID (default: 0.0.7729011).
-        #[arg(
-            long,
-            env = "NODALYNC_HEDERA_CONTRACT_ID",
-            default_value = "0.0.7729011"
-        )]
-        hedera_contract_id: String,
-
-        /// Hedera network (testnet, mainnet, previewnet).
-        #[arg(long, env = "NODALYNC_HEDERA_NETWORK", default_value = "testnet")]
-        hedera_network: String,
+    /// Pull the sync bundle a peer holds for us and import it.
+    Pull {
+        /// Peer holding the bundle (ndl1... or hex).
+        peer: String,
     },
+}
 
-    // =========================================================================
-    // Discovery Commands
-    // =========================================================================
-    /// Search for content by keyword.
+/// Network diagnostics subcommands.
+#[derive(Subcommand, Debug)]
+pub enum NetCommands {
+    /// Summarize a session capture.
     ///
-    /// Searches title, description, and tags of local content.
-    Search {
-        /// Search query (matches title, description, and tags).
-        query: String,
-
-        /// Filter by content type.
-        #[arg(short = 't', long)]
-        content_type: Option<ContentTypeArg>,
+    /// Reads a capture (written with `capture = true` under [network]) and
+    /// shows, per session, the message types seen, the error rate, and the
+    /// payments sent and received, overall and per peer.
+    Inspect {
+        /// Capture file.
+        file: PathBuf,
+    },
+}
 
-        /// Maximum results to show.
-        #[arg(short, long, default_value = "20")]
-        limit: u32,
+/// Known peer subcommands.
+#[derive(Subcommand, Debug)]
+pub enum PeersCommands {
+    /// List known peers with their measured latency.
+    ///
+    /// Shows round-trip percentiles, jitter, loss and QoS score from the
+    /// latency probes of a running node (see [qos]), best QoS first.
+    List,
+}
 
-        /// Search across network (not just local).
-        #[arg(short, long)]
-        all: bool,
+/// Replica subcommands.
+#[derive(Subcommand, Debug)]
+pub enum ReplicaCommands {
+    /// Export our published catalog for a replica to serve.
+    Export {
+        /// Replica's peer ID (ndl1... or hex).
+        replica: String,
+        /// File to write.
+        file: PathBuf,
+        /// Days until the delegation expires.
+        #[arg(long, default_value = "30")]
+        days: u64,
     },
 
-    // =========================================================================
-    // Shell Completion Commands
-    // =========================================================================
-    /// Generate shell completions.
-    ///
-    /// Outputs shell completion scripts for various shells.
-    Completions {
-        /// Shell to generate completions for.
-        shell: CompletionShell,
+    /// Import a primary's catalog to serve it.
+    Import {
+        /// File to read.
+        file: PathBuf,
     },
+
+    /// List the primaries whose catalogs we serve.
+    List,
 }
 
 /// Shell types for completion generation.
@@ -498,6 +1876,33 @@ impl std::fmt::Display for VisibilityArg {
     }
 }
 
+/// Report reason argument for clap.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ReportReasonArg {
+    /// Unsolicited or misleading content.
+    Spam,
+    /// Harassment or abusive content.
+    Abuse,
+    /// Infringes someone's copyright.
+    Copyright,
+    /// Illegal content.
+    Illegal,
+    /// Anything else; explain with --comment.
+    Other,
+}
+
+impl From<ReportReasonArg> for nodalync_types::ReportReason {
+    fn from(arg: ReportReasonArg) -> Self {
+        match arg {
+            ReportReasonArg::Spam => nodalync_types::ReportReason::Spam,
+            ReportReasonArg::Abuse => nodalync_types::ReportReason::Abuse,
+            ReportReasonArg::Copyright => nodalync_types::ReportReason::Copyright,
+            ReportReasonArg::Illegal => nodalync_types::ReportReason::Illegal,
+            ReportReasonArg::Other => nodalync_types::ReportReason::Other,
+        }
+    }
+}
+
 /// Content type argument for clap.
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum ContentTypeArg {
@@ -509,6 +1914,8 @@ pub enum ContentTypeArg {
     L2,
     /// Insights/synthesis.
     L3,
+    /// Curated collections.
+    Collection,
 }
 
 impl From<ContentTypeArg> for nodalync_types::ContentType {
@@ -518,6 +1925,7 @@ impl From<ContentTypeArg> for nodalync_types::ContentType {
             ContentTypeArg::L1 => nodalync_types::ContentType::L1,
             ContentTypeArg::L2 => nodalync_types::ContentType::L2,
             ContentTypeArg::L3 => nodalync_types::ContentType::L3,
+            ContentTypeArg::Collection => nodalync_types::ContentType::Collection,
         }
     }
 }
@@ -543,6 +1951,142 @@ fn parse_non_negative_price(s: &str) -> Result<f64, String> {
     Ok(value)
 }
 
+/// Parse a section price given as `HEADING=HBAR`.
+fn parse_section_price(s: &str) -> Result<(String, f64), String> {
+    let (heading, price) = s
+        .rsplit_once('=')
+        .ok_or_else(|| format!("'{}' is not HEADING=PRICE", s))?;
+    let heading = heading.trim();
+    if heading.is_empty() {
+        return Err("Section heading cannot be empty".to_string());
+    }
+    Ok((heading.to_string(), parse_non_negative_price(price.trim())?))
+}
+
+/// Parse a ledger account name.
+fn parse_ledger_account(s: &str) -> Result<LedgerAccount, String> {
+    LedgerAccount::parse(s).ok_or_else(|| {
+        let names: Vec<&str> = LedgerAccount::ALL.iter().map(|a| a.as_str()).collect();
+        format!(
+            "'{}' is not a ledger account (expected one of: {})",
+            s,
+            names.join(", ")
+        )
+    })
+}
+
+/// Parse a webhook delivery status.
+fn parse_webhook_status(s: &str) -> Result<WebhookDeliveryStatus, String> {
+    WebhookDeliveryStatus::parse(s).ok_or_else(|| {
+        format!(
+            "'{}' is not a webhook delivery status (expected pending, delivered or failed)",
+            s
+        )
+    })
+}
+
+/// Parse a digest period.
+fn parse_digest_period(s: &str) -> Result<DigestPeriod, String> {
+    DigestPeriod::parse(s)
+        .ok_or_else(|| format!("'{}' is not a digest period (expected daily or weekly)", s))
+}
+
+/// Parse an invoice direction.
+fn parse_invoice_direction(s: &str) -> Result<InvoiceDirection, String> {
+    InvoiceDirection::parse(s).ok_or_else(|| {
+        format!(
+            "'{}' is not an invoice direction (expected issued or received)",
+            s
+        )
+    })
+}
+
+/// Parse an RFC 3339 time into Unix milliseconds.
+///
+/// Seconds are optional, so `2025-07-01T09:00Z`, `2025-07-01T09:00:30.5Z`
+/// and `2025-07-01T11:00+02:00` are all accepted.
+fn parse_publish_time(s: &str) -> Result<u64, String> {
+    let invalid = || {
+        format!(
+            "'{}' is not a valid time (expected e.g. 2025-07-01T09:00Z)",
+            s
+        )
+    };
+    let number = |part: &str| -> Result<i64, String> {
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        part.parse().map_err(|_| invalid())
+    };
+
+    let (date, rest) = s.split_once(['T', 't', ' ']).ok_or_else(invalid)?;
+
+    // Split the UTC offset from the time of day
+    let (time, offset_mins) = match rest.strip_suffix(['Z', 'z']) {
+        Some(time) => (time, 0),
+        None => {
+            let idx = rest.rfind(['+', '-']).ok_or_else(invalid)?;
+            let (time, offset) = rest.split_at(idx);
+            let (hours, mins) = offset[1..].split_once(':').ok_or_else(invalid)?;
+            let (hours, mins) = (number(hours)?, number(mins)?);
+            if hours > 23 || mins > 59 {
+                return Err(invalid());
+            }
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            (time, sign * (hours * 60 + mins))
+        }
+    };
+
+    let date: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = date[..] else {
+        return Err(invalid());
+    };
+    let (year, month, day) = (number(year)?, number(month)?, number(day)?);
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return Err(invalid()),
+    };
+    if !(1..=month_days).contains(&day) {
+        return Err(invalid());
+    }
+
+    let time: Vec<&str> = time.split(':').collect();
+    let (hour, minute, second) = match time[..] {
+        [hour, minute] => (hour, minute, "0"),
+        [hour, minute, second] => (hour, minute, second),
+        _ => return Err(invalid()),
+    };
+    let (second, fraction) = second.split_once('.').unwrap_or((second, ""));
+    let (hour, minute, second) = (number(hour)?, number(minute)?, number(second)?);
+    if hour > 23 || minute > 59 || second > 59 {
+        return Err(invalid());
+    }
+    let millis = if fraction.is_empty() {
+        0
+    } else {
+        let digits: String = fraction.chars().chain("00".chars()).take(3).collect();
+        number(&digits)?
+    };
+
+    // Days since the epoch from a civil date (Howard Hinnant's algorithm)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    let secs = days * 86_400 + hour * 3600 + minute * 60 + second - offset_mins * 60;
+    if secs < 0 {
+        return Err(format!("'{}' is before 1970", s));
+    }
+    Ok(secs as u64 * 1000 + millis as u64)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -630,4 +2174,899 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), 0.0);
     }
+
+    #[test]
+    fn test_parse_publish_time() {
+        assert_eq!(parse_publish_time("1970-01-01T00:00Z").unwrap(), 0);
+        assert_eq!(
+            parse_publish_time("2025-07-01T09:00Z").unwrap(),
+            1_751_360_400_000
+        );
+        assert_eq!(
+            parse_publish_time("2025-07-01T11:00:00+02:00").unwrap(),
+            1_751_360_400_000
+        );
+        assert_eq!(
+            parse_publish_time("2024-02-29T00:00:01.25Z").unwrap(),
+            1_709_164_801_250
+        );
+
+        assert!(parse_publish_time("2025-07-01").is_err());
+        assert!(parse_publish_time("2025-07-01T09:00").is_err());
+        assert!(parse_publish_time("2025-02-29T09:00Z").is_err());
+        assert!(parse_publish_time("2025-07-01T24:00Z").is_err());
+        assert!(parse_publish_time("tomorrow").is_err());
+    }
+
+    #[test]
+    fn test_clap_publish_at() {
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "publish",
+            "file.txt",
+            "--at",
+            "2025-07-01T09:00Z",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Publish(PublishArgs {
+                at: Some(1_751_360_400_000),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_clap_publish_fields() {
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "publish",
+            "paper.pdf",
+            "--schema",
+            "nodalync:schema/citation/v1",
+            "--fields",
+            r#"{"authors":["Ada"],"year":1843}"#,
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Publish(PublishArgs {
+                schema,
+                fields,
+                tags,
+                ..
+            }) => {
+                assert_eq!(schema.as_deref(), Some("nodalync:schema/citation/v1"));
+                assert!(fields.unwrap().contains("Ada"));
+                assert!(tags.is_empty());
+            }
+            _ => panic!("expected publish"),
+        }
+
+        // Fields need a schema
+        assert!(
+            Cli::try_parse_from(["nodalync", "publish", "paper.pdf", "--fields", "{}"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_clap_publish_suggest_price() {
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "publish",
+            "paper.pdf",
+            "--suggest-price",
+            "--tag",
+            "science",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Publish(PublishArgs {
+                suggest_price: true,
+                ..
+            })
+        ));
+
+        // Suggesting a price doesn't publish, so it takes no price or time
+        assert!(Cli::try_parse_from([
+            "nodalync",
+            "publish",
+            "paper.pdf",
+            "--suggest-price",
+            "--price",
+            "0.5",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_clap_tags() {
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "publish",
+            "paper.pdf",
+            "--tag",
+            "science/biology",
+            "--tag",
+            "genetics",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Publish(PublishArgs { tags, .. }) => {
+                assert_eq!(tags, vec!["science/biology", "genetics"]);
+            }
+            _ => panic!("expected publish"),
+        }
+
+        let cli = Cli::try_parse_from(["nodalync", "tags", "sci", "--limit", "5"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Tags(TagsArgs {
+                prefix: Some(ref p),
+                limit: 5,
+            }) if p == "sci"
+        ));
+    }
+
+    #[test]
+    fn test_clap_retention_status() {
+        let cli = Cli::try_parse_from(["nodalync", "retention", "status"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Retention(RetentionArgs {
+                command: RetentionCommands::Status
+            })
+        ));
+        assert!(Cli::try_parse_from(["nodalync", "retention"]).is_err());
+    }
+
+    #[test]
+    fn test_clap_webhooks_list() {
+        let cli =
+            Cli::try_parse_from(["nodalync", "webhooks", "list", "--status", "failed"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Webhooks(WebhooksArgs {
+                command: WebhookCommands::List {
+                    status: Some(WebhookDeliveryStatus::Failed),
+                    limit: 20,
+                }
+            })
+        ));
+        assert!(Cli::try_parse_from(["nodalync", "webhooks", "list", "--status", "lost"]).is_err());
+    }
+
+    #[test]
+    fn test_clap_standing_orders() {
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "standing-orders",
+            "add",
+            "--tag",
+            "science/biology",
+            "--max-price",
+            "0.5",
+            "--min-reputation",
+            "-10",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::StandingOrders(StandingOrdersArgs {
+                command:
+                    StandingOrderCommands::Add {
+                        tag,
+                        max_price,
+                        min_reputation,
+                        budget,
+                    },
+            }) => {
+                assert_eq!(tag, "science/biology");
+                assert_eq!(max_price, 0.5);
+                assert_eq!(min_reputation, Some(-10));
+                assert_eq!(budget, None);
+            }
+            _ => panic!("expected standing-orders add"),
+        }
+
+        let cli = Cli::try_parse_from(["nodalync", "standing-orders", "purchases", "--order", "3"])
+            .unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::StandingOrders(StandingOrdersArgs {
+                command: StandingOrderCommands::Purchases {
+                    order: Some(3),
+                    limit: 20,
+                }
+            })
+        ));
+        assert!(Cli::try_parse_from([
+            "nodalync",
+            "standing-orders",
+            "add",
+            "--tag",
+            "science",
+            "--max-price",
+            "-1"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_clap_tenants() {
+        let cli = Cli::try_parse_from([
+            "nodalync", "tenants", "create", "acme", "--name", "Acme", "--budget", "2",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Tenants(TenantsArgs {
+                command: TenantCommands::Create { id, name, budget },
+            }) => {
+                assert_eq!(id, "acme");
+                assert_eq!(name.as_deref(), Some("Acme"));
+                assert_eq!(budget, Some(2.0));
+            }
+            _ => panic!("expected tenants create"),
+        }
+
+        let cli = Cli::try_parse_from(["nodalync", "tenants", "budget", "acme"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Tenants(TenantsArgs {
+                command: TenantCommands::Budget { budget: None, .. }
+            })
+        ));
+        let cli = Cli::try_parse_from(["nodalync", "tenants", "rotate-key", "acme"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Tenants(TenantsArgs {
+                command: TenantCommands::RotateKey { .. }
+            })
+        ));
+
+        let cli =
+            Cli::try_parse_from(["nodalync", "mcp-server", "--tenant-key", "ndk_abc"]).unwrap();
+        match cli.command {
+            Commands::McpServer(McpServerArgs { tenant_key, .. }) => {
+                assert_eq!(tenant_key.as_deref(), Some("ndk_abc"))
+            }
+            _ => panic!("expected mcp-server"),
+        }
+    }
+
+    #[test]
+    fn test_clap_digest() {
+        let cli = Cli::try_parse_from(["nodalync", "digest"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Digest(DigestArgs {
+                period: DigestPeriod::Daily,
+                send: false,
+            })
+        ));
+
+        let cli =
+            Cli::try_parse_from(["nodalync", "digest", "--period", "weekly", "--send"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Digest(DigestArgs {
+                period: DigestPeriod::Weekly,
+                send: true,
+            })
+        ));
+        assert!(Cli::try_parse_from(["nodalync", "digest", "--period", "monthly"]).is_err());
+    }
+
+    #[test]
+    fn test_clap_import_ipfs() {
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "import",
+            "ipfs",
+            "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e",
+            "--price",
+            "0.5",
+            "--pin",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Import(ImportArgs {
+                command:
+                    ImportCommands::Ipfs {
+                        cid,
+                        price,
+                        visibility,
+                        pin,
+                        ..
+                    },
+            }) => {
+                assert!(cid.starts_with("bafkrei"));
+                assert_eq!(price, Some(0.5));
+                assert!(matches!(visibility, VisibilityArg::Shared));
+                assert!(pin);
+            }
+            _ => panic!("Expected import ipfs"),
+        }
+
+        assert!(Cli::try_parse_from(["nodalync", "import", "ipfs"]).is_err());
+    }
+
+    #[test]
+    fn test_clap_sync() {
+        let cli = Cli::try_parse_from(["nodalync", "sync", "export", "laptop.sync"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Sync(SyncArgs {
+                command: SyncCommands::Export { ref file }
+            }) if file == &PathBuf::from("laptop.sync")
+        ));
+
+        let cli = Cli::try_parse_from(["nodalync", "sync", "pull", "ndl1abc"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Sync(SyncArgs {
+                command: SyncCommands::Pull { ref peer }
+            }) if peer == "ndl1abc"
+        ));
+        assert!(Cli::try_parse_from(["nodalync", "sync", "push"]).is_err());
+    }
+
+    #[test]
+    fn test_clap_replica() {
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "replica",
+            "export",
+            "ndl1abc",
+            "catalog.replica",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Replica(ReplicaArgs {
+                command: ReplicaCommands::Export { ref replica, days: 30, .. }
+            }) if replica == "ndl1abc"
+        ));
+
+        let cli = Cli::try_parse_from(["nodalync", "replica", "list"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Replica(ReplicaArgs {
+                command: ReplicaCommands::List
+            })
+        ));
+        assert!(Cli::try_parse_from(["nodalync", "replica", "import"]).is_err());
+    }
+
+    #[test]
+    fn test_clap_replay() {
+        let cli = Cli::try_parse_from(["nodalync", "replay", "replay.log"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Replay(ReplayArgs { ref log }) if log == &PathBuf::from("replay.log")
+        ));
+        assert!(Cli::try_parse_from(["nodalync", "replay"]).is_err());
+    }
+
+    #[test]
+    fn test_clap_net_inspect() {
+        let cli = Cli::try_parse_from(["nodalync", "net", "inspect", "capture.ndlcap"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Net(NetArgs {
+                command: NetCommands::Inspect { ref file }
+            }) if file == &PathBuf::from("capture.ndlcap")
+        ));
+        assert!(Cli::try_parse_from(["nodalync", "net", "inspect"]).is_err());
+    }
+
+    #[test]
+    fn test_clap_peers_list() {
+        let cli = Cli::try_parse_from(["nodalync", "peers", "list"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Peers(PeersArgs {
+                command: PeersCommands::List
+            })
+        ));
+        assert!(Cli::try_parse_from(["nodalync", "peers"]).is_err());
+    }
+
+    #[test]
+    fn test_clap_availability() {
+        let cli = Cli::try_parse_from(["nodalync", "availability", "--failing"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Availability(AvailabilityArgs { failing: true })
+        ));
+        let cli = Cli::try_parse_from(["nodalync", "availability"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Availability(AvailabilityArgs { failing: false })
+        ));
+    }
+
+    #[test]
+    fn test_clap_ledger() {
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "ledger",
+            "--account",
+            "channels",
+            "--since",
+            "2025-07-01T09:00Z",
+            "--export",
+            "ledger.csv",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Ledger(LedgerArgs {
+                account,
+                since,
+                limit,
+                export,
+            }) => {
+                assert_eq!(account, Some(LedgerAccount::Channels));
+                assert!(since.is_some());
+                assert_eq!(limit, 20);
+                assert_eq!(export, Some(PathBuf::from("ledger.csv")));
+            }
+            _ => panic!("expected ledger"),
+        }
+
+        assert!(Cli::try_parse_from(["nodalync", "ledger", "--account", "savings"]).is_err());
+    }
+
+    #[test]
+    fn test_clap_invoices() {
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "create-invoice",
+            "2.5",
+            "--memo",
+            "Consulting",
+            "--expires-in",
+            "48",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::CreateInvoice(CreateInvoiceArgs {
+                amount,
+                memo,
+                reference,
+                expires_in,
+            }) => {
+                assert_eq!(amount, 2.5);
+                assert_eq!(memo.as_deref(), Some("Consulting"));
+                assert!(reference.is_none());
+                assert_eq!(expires_in, Some(48));
+            }
+            _ => panic!("expected create-invoice"),
+        }
+
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "list-invoices",
+            "--direction",
+            "received",
+            "--unpaid",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::ListInvoices(ListInvoicesArgs { direction, unpaid }) => {
+                assert_eq!(direction, Some(InvoiceDirection::Received));
+                assert!(unpaid);
+            }
+            _ => panic!("expected list-invoices"),
+        }
+
+        assert!(
+            Cli::try_parse_from(["nodalync", "list-invoices", "--direction", "outgoing"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_clap_groups() {
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "create-group",
+            "Reviewers",
+            "--member",
+            "ndl1a",
+            "--member",
+            "ndl1b",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::CreateGroup(CreateGroupArgs { name, members }) => {
+                assert_eq!(name, "Reviewers");
+                assert_eq!(members, vec!["ndl1a", "ndl1b"]);
+            }
+            _ => panic!("expected create-group"),
+        }
+
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "update-group",
+            "abc",
+            "--add",
+            "ndl1c",
+            "--remove",
+            "ndl1a",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::UpdateGroup(UpdateGroupArgs { id, add, remove }) => {
+                assert_eq!(id, "abc");
+                assert_eq!(add, vec!["ndl1c"]);
+                assert_eq!(remove, vec!["ndl1a"]);
+            }
+            _ => panic!("expected update-group"),
+        }
+
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "visibility",
+            "abc",
+            "--level",
+            "unlisted",
+            "--group",
+            "def",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Visibility(VisibilityArgs { groups, .. }) => assert_eq!(groups, vec!["def"]),
+            _ => panic!("expected visibility"),
+        }
+    }
+
+    #[test]
+    fn test_clap_pricing() {
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "pricing",
+            "abc",
+            "--surge-max",
+            "2.5",
+            "--surge-step",
+            "500",
+            "--window",
+            "15",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Pricing(PricingArgs {
+                surge_max,
+                surge_step,
+                window,
+                decay_from,
+                fixed,
+                ..
+            }) => {
+                assert_eq!(surge_max, Some(2.5));
+                assert_eq!(surge_step, Some(500));
+                assert_eq!(window, Some(15));
+                assert_eq!(decay_from, None);
+                assert!(!fixed);
+            }
+            _ => panic!("expected pricing"),
+        }
+
+        // A surge needs its step, and one rule at a time
+        assert!(Cli::try_parse_from(["nodalync", "pricing", "abc", "--surge-max", "2"]).is_err());
+        assert!(Cli::try_parse_from([
+            "nodalync",
+            "pricing",
+            "abc",
+            "--decay-from",
+            "5",
+            "--decay-hours",
+            "2",
+            "--fixed",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_clap_sections() {
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "sections",
+            "abc",
+            "--price",
+            "Results = 0.5",
+            "--price",
+            "Key=Value=1",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Sections(SectionsArgs { prices, clear, .. }) => {
+                assert_eq!(
+                    prices,
+                    vec![("Results".to_string(), 0.5), ("Key=Value".to_string(), 1.0)]
+                );
+                assert!(!clear);
+            }
+            _ => panic!("expected sections"),
+        }
+
+        // A price is needed for each heading
+        assert!(
+            Cli::try_parse_from(["nodalync", "sections", "abc", "--price", "Results"]).is_err()
+        );
+        assert!(Cli::try_parse_from(["nodalync", "sections", "abc", "--price", "=1"]).is_err());
+
+        let cli = Cli::try_parse_from(["nodalync", "query", "abc", "--section", "2"]).unwrap();
+        match cli.command {
+            Commands::Query(QueryArgs { section, .. }) => assert_eq!(section, Some(2)),
+            _ => panic!("expected query"),
+        }
+    }
+
+    #[test]
+    fn test_clap_metering() {
+        let cli = Cli::try_parse_from(["nodalync", "metering", "abc", "--unit-kb", "4"]).unwrap();
+        match cli.command {
+            Commands::Metering(MeteringArgs { unit_kb, off, .. }) => {
+                assert_eq!(unit_kb, Some(4));
+                assert!(!off);
+            }
+            _ => panic!("expected metering"),
+        }
+        assert!(Cli::try_parse_from(["nodalync", "metering", "abc", "--unit-kb", "0"]).is_err());
+        assert!(
+            Cli::try_parse_from(["nodalync", "metering", "abc", "--unit-kb", "4", "--off"])
+                .is_err()
+        );
+
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "query",
+            "abc",
+            "--metered",
+            "--until",
+            "## Results",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Query(QueryArgs {
+                metered,
+                until,
+                max_bytes,
+                ..
+            }) => {
+                assert!(metered);
+                assert_eq!(until.as_deref(), Some("## Results"));
+                assert_eq!(max_bytes, None);
+            }
+            _ => panic!("expected query"),
+        }
+
+        // The cut-offs only apply to metered reads
+        assert!(Cli::try_parse_from(["nodalync", "query", "abc", "--max-bytes", "10"]).is_err());
+        assert!(
+            Cli::try_parse_from(["nodalync", "query", "abc", "--metered", "--section", "1"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_clap_storefront() {
+        let cli = Cli::try_parse_from(["nodalync", "storefront", "ndl1abc", "--category", "birds"])
+            .unwrap();
+        match cli.command {
+            Commands::Storefront(StorefrontArgs {
+                publisher,
+                category,
+                publish,
+            }) => {
+                assert_eq!(publisher.as_deref(), Some("ndl1abc"));
+                assert_eq!(category.as_deref(), Some("birds"));
+                assert!(!publish);
+            }
+            _ => panic!("expected storefront"),
+        }
+        assert!(Cli::try_parse_from(["nodalync", "storefront", "--publish"]).is_ok());
+        assert!(Cli::try_parse_from(["nodalync", "storefront", "ndl1abc", "--publish"]).is_err());
+    }
+
+    #[test]
+    fn test_clap_library() {
+        let cli = Cli::try_parse_from(["nodalync", "library", "owls"]).unwrap();
+        match cli.command {
+            Commands::Library(LibraryArgs {
+                search,
+                open,
+                output,
+            }) => {
+                assert_eq!(search.as_deref(), Some("owls"));
+                assert!(open.is_none());
+                assert!(output.is_none());
+            }
+            _ => panic!("expected library"),
+        }
+        assert!(
+            Cli::try_parse_from(["nodalync", "library", "--open", "abc", "-o", "owls.txt"]).is_ok()
+        );
+        assert!(Cli::try_parse_from(["nodalync", "library", "owls", "--open", "abc"]).is_err());
+        assert!(Cli::try_parse_from(["nodalync", "library", "-o", "owls.txt"]).is_err());
+    }
+
+    #[test]
+    fn test_clap_quota() {
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "quota",
+            "abc",
+            "--queries",
+            "100",
+            "--bytes",
+            "1048576",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Quota(QuotaArgs {
+                queries,
+                bytes,
+                off,
+                reset,
+                ..
+            }) => {
+                assert_eq!(queries, Some(100));
+                assert_eq!(bytes, Some(1_048_576));
+                assert!(!off);
+                assert!(reset.is_none());
+            }
+            _ => panic!("expected quota"),
+        }
+        assert!(Cli::try_parse_from(["nodalync", "quota", "abc", "--queries", "0"]).is_err());
+        assert!(
+            Cli::try_parse_from(["nodalync", "quota", "abc", "--bytes", "1", "--off"]).is_err()
+        );
+        assert!(Cli::try_parse_from(["nodalync", "quota", "abc", "--reset", "ndl1abc"]).is_ok());
+    }
+
+    #[test]
+    fn test_clap_trial() {
+        let cli = Cli::try_parse_from([
+            "nodalync", "trial", "abc", "--reads", "3", "--bytes", "4096",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Trial(TrialArgs {
+                reads, bytes, off, ..
+            }) => {
+                assert_eq!(reads, Some(3));
+                assert_eq!(bytes, Some(4096));
+                assert!(!off);
+            }
+            _ => panic!("expected trial"),
+        }
+        assert!(Cli::try_parse_from(["nodalync", "trial", "abc", "--reads", "0"]).is_err());
+        assert!(Cli::try_parse_from(["nodalync", "trial", "abc", "--bytes", "10"]).is_err());
+        assert!(
+            Cli::try_parse_from(["nodalync", "trial", "abc", "--reads", "1", "--off"]).is_err()
+        );
+
+        let cli = Cli::try_parse_from(["nodalync", "query", "abc", "--trial"]).unwrap();
+        match cli.command {
+            Commands::Query(QueryArgs { trial, .. }) => assert!(trial),
+            _ => panic!("expected query"),
+        }
+        assert!(Cli::try_parse_from(["nodalync", "query", "abc", "--trial", "--metered"]).is_err());
+    }
+
+    #[test]
+    fn test_clap_referral() {
+        let cli = Cli::try_parse_from(["nodalync", "referral", "abc", "--percent", "2.5"]).unwrap();
+        match cli.command {
+            Commands::Referral(ReferralArgs { percent, off, .. }) => {
+                assert_eq!(percent, Some(2.5));
+                assert!(!off);
+            }
+            _ => panic!("expected referral"),
+        }
+        assert!(
+            Cli::try_parse_from(["nodalync", "referral", "abc", "--percent", "1", "--off"])
+                .is_err()
+        );
+
+        let cli =
+            Cli::try_parse_from(["nodalync", "query", "abc", "--referrer", "ndl1xyz"]).unwrap();
+        match cli.command {
+            Commands::Query(QueryArgs { referrer, .. }) => {
+                assert_eq!(referrer.as_deref(), Some("ndl1xyz"))
+            }
+            _ => panic!("expected query"),
+        }
+        assert!(Cli::try_parse_from([
+            "nodalync",
+            "query",
+            "abc",
+            "--trial",
+            "--referrer",
+            "ndl1xyz"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_clap_report_usage() {
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "report-usage",
+            "abc123",
+            "--bytes-read",
+            "4096",
+            "--rating",
+            "4",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::ReportUsage(ReportUsageArgs {
+                hash,
+                bytes_read,
+                context_tokens,
+                rating,
+            }) => {
+                assert_eq!(hash, "abc123");
+                assert_eq!(bytes_read, 4096);
+                assert!(context_tokens.is_none());
+                assert_eq!(rating, Some(4));
+            }
+            _ => panic!("expected report-usage"),
+        }
+
+        assert!(Cli::try_parse_from([
+            "nodalync",
+            "report-usage",
+            "abc123",
+            "--bytes-read",
+            "1",
+            "--rating",
+            "6"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_clap_search_filters() {
+        let cli = Cli::try_parse_from([
+            "nodalync",
+            "search",
+            "rust",
+            "--min-price",
+            "0.5",
+            "--max-price",
+            "2",
+            "--tag",
+            "lang",
+            "--tag",
+            "systems",
+            "--after",
+            "2025-01-01T00:00Z",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Search(SearchArgs {
+                min_price,
+                max_price,
+                tags,
+                owner,
+                after,
+                ..
+            }) => {
+                assert_eq!(min_price, Some(0.5));
+                assert_eq!(max_price, Some(2.0));
+                assert_eq!(tags, vec!["lang", "systems"]);
+                assert!(owner.is_none());
+                assert_eq!(after, Some(1_735_689_600_000));
+            }
+            _ => panic!("expected search"),
+        }
+
+        assert!(
+            Cli::try_parse_from(["nodalync", "search", "rust", "--after", "yesterday"]).is_err()
+        );
+    }
 }
@@ -0,0 +1,216 @@
+//! Attribution certificate commands.
+
+use std::path::Path;
+
+use nodalync_crypto::PeerId;
+use nodalync_types::AttributionCertificate;
+
+use crate::config::CliConfig;
+use crate::context::{parse_hash, NodeContext};
+use crate::error::{CliError, CliResult};
+use crate::output::{AttributionOutput, AttributionSourceRow, OutputFormat, Render};
+use crate::prompt::get_identity_password;
+
+/// Issue a signed attribution certificate for our own L3 content.
+///
+/// Attestations are included when a settlement layer is configured.
+pub async fn attribution(
+    config: CliConfig,
+    format: OutputFormat,
+    hash_str: &str,
+    export: Option<&Path>,
+) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+    let mut ctx = NodeContext::with_network(config).await?;
+    if !ctx.ops.has_private_key() {
+        load_signing_key(&mut ctx)?;
+    }
+
+    let certificate = ctx.ops.attribution_certificate(&hash).await?;
+    if let Some(path) = export {
+        write_certificate(path, &certificate)?;
+    }
+
+    let signers = vec![ctx.peer_id()];
+    Ok(attribution_output(certificate, signers, "issued", export).render(format))
+}
+
+/// Verify an attribution certificate file.
+pub fn verify_attribution(
+    config: CliConfig,
+    format: OutputFormat,
+    file: &Path,
+) -> CliResult<String> {
+    let certificate = read_certificate(file)?;
+    let ctx = NodeContext::local_read_only(config)?;
+
+    let signers = ctx.ops.verify_attribution_certificate(&certificate)?;
+    Ok(attribution_output(certificate, signers, "verified", None).render(format))
+}
+
+/// Add our signature to a certificate file as the owner of a source.
+pub fn endorse_attribution(
+    config: CliConfig,
+    format: OutputFormat,
+    file: &Path,
+) -> CliResult<String> {
+    let mut certificate = read_certificate(file)?;
+    let mut ctx = NodeContext::local(config)?;
+    load_signing_key(&mut ctx)?;
+
+    ctx.ops.endorse_attribution_certificate(&mut certificate)?;
+    write_certificate(file, &certificate)?;
+
+    let signers = ctx.ops.verify_attribution_certificate(&certificate)?;
+    Ok(attribution_output(certificate, signers, "endorsed", Some(file)).render(format))
+}
+
+fn read_certificate(file: &Path) -> CliResult<AttributionCertificate> {
+    if !file.exists() {
+        return Err(CliError::FileNotFound(file.display().to_string()));
+    }
+    serde_json::from_str(&std::fs::read_to_string(file)?)
+        .map_err(|e| CliError::User(format!("Not an attribution certificate: {}", e)))
+}
+
+fn write_certificate(path: &Path, certificate: &AttributionCertificate) -> CliResult<()> {
+    std::fs::write(path, serde_json::to_string_pretty(certificate)?)?;
+    Ok(())
+}
+
+/// Load the private key; certificates are signed by their owners.
+fn load_signing_key(ctx: &mut NodeContext) -> CliResult<()> {
+    let password = get_identity_password()?;
+    let (private_key, _) = ctx.ops.state.identity.load(&password).map_err(|e| {
+        if matches!(e, nodalync_store::StoreError::Encryption(_)) {
+            CliError::User(e.to_string())
+        } else {
+            CliError::from(e)
+        }
+    })?;
+    ctx.ops.set_private_key(private_key);
+    Ok(())
+}
+
+fn attribution_output(
+    certificate: AttributionCertificate,
+    signers: Vec<PeerId>,
+    operation: &str,
+    path: Option<&Path>,
+) -> AttributionOutput {
+    AttributionOutput {
+        operation: operation.to_string(),
+        hash: certificate.content_hash.to_string(),
+        title: certificate.title.clone(),
+        owner: certificate.owner.to_string(),
+        sources: certificate
+            .sources
+            .iter()
+            .map(|source| AttributionSourceRow {
+                hash: source.content_hash.to_string(),
+                owner: source.owner.to_string(),
+                weight: source.weight,
+            })
+            .collect(),
+        attestations: certificate.attestations.len(),
+        signers: signers.iter().map(|peer| peer.to_string()).collect(),
+        path: path.map(|path| path.display().to_string()),
+        certificate,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use crate::commands::publish::publish;
+    use crate::commands::synthesize::synthesize;
+    use nodalync_crypto::content_hash;
+    use nodalync_types::Visibility;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_attribution_issue_and_verify() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let file = temp_dir.path().join("source.txt");
+        std::fs::write(&file, "Source material").unwrap();
+        publish(
+            config.clone(),
+            OutputFormat::Json,
+            &file,
+            Some(0.0),
+            Visibility::Private,
+            Some("Source".to_string()),
+            None,
+            None,
+            None,
+            None,
+            vec![],
+        )
+        .await
+        .unwrap();
+        let source = content_hash(b"Source material").to_string();
+
+        let insight = temp_dir.path().join("insight.txt");
+        std::fs::write(&insight, "An insight").unwrap();
+        synthesize(
+            config.clone(),
+            OutputFormat::Json,
+            std::slice::from_ref(&source),
+            &insight,
+            Some("Insight".to_string()),
+            None,
+            false,
+        )
+        .await
+        .unwrap();
+        let l3 = content_hash(b"An insight").to_string();
+
+        // Only derived content gets a certificate
+        assert!(
+            attribution(config.clone(), OutputFormat::Json, &source, None)
+                .await
+                .is_err()
+        );
+
+        let cert_file = temp_dir.path().join("attribution.json");
+        let result = attribution(config.clone(), OutputFormat::Human, &l3, Some(&cert_file))
+            .await
+            .unwrap();
+        assert!(result.contains("Attribution certificate issued"));
+        let json = std::fs::read_to_string(&cert_file).unwrap();
+        assert!(json.contains("\"@type\": \"AttributionCertificate\""));
+
+        let result = verify_attribution(config.clone(), OutputFormat::Json, &cert_file).unwrap();
+        assert!(result.contains("\"operation\": \"verified\""));
+        assert!(result.contains(&source));
+
+        // Tampered certificates fail
+        std::fs::write(&cert_file, json.replace("Insight", "Forgery")).unwrap();
+        assert!(verify_attribution(config.clone(), OutputFormat::Json, &cert_file).is_err());
+
+        // Another node owns none of the sources, so it can't endorse it,
+        // but it can still verify it
+        std::fs::write(&cert_file, &json).unwrap();
+        let other_dir = TempDir::new().unwrap();
+        let other = setup_config(&other_dir);
+        init(other.clone(), OutputFormat::Human, false).unwrap();
+        assert!(endorse_attribution(other.clone(), OutputFormat::Json, &cert_file).is_err());
+        assert!(verify_attribution(other, OutputFormat::Json, &cert_file).is_ok());
+    }
+}
@@ -0,0 +1,122 @@
+//! Content availability command.
+//!
+//! `availability` shows what the availability checks of a running node
+//! (`[availability]`) found: whether each piece of our shared content
+//! could be dialed and previewed by a remote peer, and how often it failed.
+
+use nodalync_crypto::peer_id_to_string;
+use nodalync_store::{AvailabilityStore, ManifestStore};
+
+use crate::config::CliConfig;
+use crate::context::NodeContext;
+use crate::error::CliResult;
+use crate::output::{AvailabilityInfo, AvailabilityOutput, OutputFormat, Render};
+
+/// Execute the availability command.
+pub fn list_availability(
+    config: CliConfig,
+    format: OutputFormat,
+    failing: bool,
+) -> CliResult<String> {
+    let ctx = NodeContext::local_read_only(config)?;
+    let state = &ctx.ops.state;
+
+    let records = state.availability.list()?;
+    let reachable = records.iter().filter(|r| r.last.reachable).count();
+    let total = records.len();
+
+    let mut content = Vec::new();
+    for record in records {
+        if failing && record.last.reachable {
+            continue;
+        }
+        let title = state
+            .manifests
+            .load(&record.last.hash)?
+            .map(|manifest| manifest.metadata.title);
+        content.push(AvailabilityInfo {
+            hash: record.last.hash.to_string(),
+            title,
+            reachable: record.last.reachable,
+            checker: peer_id_to_string(&record.last.checker),
+            dialed: record.last.dialed,
+            latency_ms: record.last.latency_ms,
+            error: record.last.error,
+            checked_at: record.last.checked_at,
+            checks: record.checks,
+            failures: record.failures,
+            consecutive_failures: record.consecutive_failures,
+        });
+    }
+
+    let output = AvailabilityOutput {
+        total,
+        reachable,
+        unreachable: total - reachable,
+        content,
+    };
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use nodalync_crypto::{content_hash, PeerId};
+    use nodalync_store::AvailabilityCheck;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    #[test]
+    fn test_list_availability() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let output = list_availability(config.clone(), OutputFormat::Human, false).unwrap();
+        assert!(output.contains("No availability checks recorded"));
+
+        {
+            let ctx = NodeContext::local(config.clone()).unwrap();
+            for (data, reachable) in [(&b"up"[..], true), (&b"down"[..], false)] {
+                ctx.ops
+                    .state
+                    .availability
+                    .record_check(&AvailabilityCheck {
+                        hash: content_hash(data),
+                        checker: PeerId([5u8; 20]),
+                        reachable,
+                        dialed: reachable.then(|| "/ip4/203.0.113.5/tcp/9000".to_string()),
+                        latency_ms: reachable.then_some(120),
+                        error: (!reachable).then(|| "preview timed out".to_string()),
+                        checked_at: 1_000,
+                    })
+                    .unwrap();
+            }
+        }
+
+        let human = list_availability(config.clone(), OutputFormat::Human, false).unwrap();
+        assert!(human.contains("2 checked: 1 reachable, 1 unreachable"));
+        assert!(human.contains("preview timed out"));
+        assert!(human.contains("/ip4/203.0.113.5/tcp/9000"));
+
+        let json = list_availability(config, OutputFormat::Json, true).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["total"], 2);
+        assert_eq!(value["unreachable"], 1);
+        let content = value["content"].as_array().unwrap();
+        assert_eq!(content.len(), 1);
+        assert_eq!(content[0]["hash"], content_hash(b"down").to_string());
+        assert_eq!(content[0]["consecutive_failures"], 1);
+    }
+}
@@ -0,0 +1,74 @@
+//! Publisher bond commands.
+
+use crate::config::{ndl_to_units, CliConfig};
+use crate::context::NodeContext;
+use crate::error::{CliError, CliResult};
+use crate::output::{BondOutput, OutputFormat, Render};
+
+/// Execute the post-bond command.
+pub async fn post_bond(
+    config: CliConfig,
+    format: OutputFormat,
+    amount_ndl: f64,
+) -> CliResult<String> {
+    let amount = ndl_to_units(amount_ndl);
+
+    let ctx = NodeContext::with_network(config).await?;
+    if ctx.settlement.is_none() {
+        return Err(CliError::config(
+            "Hedera settlement not configured. Set HEDERA_ACCOUNT_ID, HEDERA_PRIVATE_KEY, \
+             and HEDERA_CONTRACT_ID environment variables.",
+        ));
+    }
+
+    let bond = ctx.ops.post_bond(amount).await?;
+
+    let output = BondOutput {
+        amount: bond.amount,
+        transaction_id: Some(bond.transaction_id),
+    };
+    Ok(output.render(format))
+}
+
+/// Execute the bond command.
+pub async fn bond(config: CliConfig, format: OutputFormat) -> CliResult<String> {
+    let ctx = NodeContext::with_network(config).await?;
+
+    let bond = ctx.ops.own_bond().await?;
+
+    let output = BondOutput {
+        amount: bond.as_ref().map_or(0, |b| b.amount),
+        transaction_id: bond
+            .map(|b| b.transaction_id)
+            .filter(|tx_id| !tx_id.is_empty()),
+    };
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bond_output() {
+        let output = BondOutput {
+            amount: 500_000_000,
+            transaction_id: Some("tx123".to_string()),
+        };
+        let human = output.render(OutputFormat::Human);
+        assert!(human.contains("Publisher Bond"));
+        assert!(human.contains("tx123"));
+
+        let json = output.render(OutputFormat::Json);
+        assert!(json.contains("\"amount\": 500000000"));
+
+        let none = BondOutput {
+            amount: 0,
+            transaction_id: None,
+        };
+        assert!(none
+            .render(OutputFormat::Human)
+            .contains("No publisher bond"));
+        assert!(!none.render(OutputFormat::Json).contains("transaction_id"));
+    }
+}
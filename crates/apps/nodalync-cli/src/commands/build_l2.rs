@@ -27,7 +27,7 @@ pub fn build_l2(
     }
 
     // Initialize context
-    let mut ctx = NodeContext::local(config)?;
+    let ctx = NodeContext::local(config)?;
 
     // Verify sources exist
     for source in &sources {
@@ -1,13 +1,17 @@
 //! Payment channel management commands.
 
 use nodalync_crypto::PeerId;
-use nodalync_ops::CloseResult;
+use nodalync_ops::{ChannelSkew, CloseResult, RebalanceAction, RebalanceOutcome};
 use nodalync_store::ChannelStore;
 
 use crate::config::CliConfig;
 use crate::context::NodeContext;
 use crate::error::{CliError, CliResult};
-use crate::output::{ChannelListOutput, ChannelOutput, ChannelSummary, OutputFormat, Render};
+use crate::ipc::{try_daemon_request, IpcRequest};
+use crate::output::{
+    ChannelListOutput, ChannelOutput, ChannelSkewSummary, ChannelSummary, OutputFormat,
+    RebalanceActionSummary, RebalanceOutput, Render,
+};
 
 /// Minimum channel deposit in HBAR.
 const MIN_CHANNEL_DEPOSIT_HBAR: f64 = 100.0;
@@ -27,8 +31,14 @@ pub async fn open_channel(
         )));
     }
 
-    // Convert HBAR to tinybars (1 HBAR = 100_000_000 tinybars)
-    let deposit_tinybars = (deposit_hbar * 100_000_000.0) as u64;
+    // Forward to the running node if there is one
+    let request = IpcRequest::OpenChannel {
+        peer_id: peer_id_str.to_string(),
+        deposit: deposit_hbar,
+    };
+    if let Some(output) = try_daemon_request(&config.base_dir(), format, request).await? {
+        return Ok(output);
+    }
 
     // Initialize context with network
     let mut ctx = NodeContext::with_network(config).await?;
@@ -36,6 +46,27 @@ pub async fn open_channel(
     // Bootstrap to connect to the network
     ctx.bootstrap().await?;
 
+    open_channel_with_context(&mut ctx, format, peer_id_str, deposit_hbar).await
+}
+
+/// Open a payment channel using an existing node context.
+pub async fn open_channel_with_context(
+    ctx: &mut NodeContext,
+    format: OutputFormat,
+    peer_id_str: &str,
+    deposit_hbar: f64,
+) -> CliResult<String> {
+    // Validate minimum deposit
+    if deposit_hbar < MIN_CHANNEL_DEPOSIT_HBAR {
+        return Err(CliError::User(format!(
+            "Minimum channel deposit is {} HBAR, got {}",
+            MIN_CHANNEL_DEPOSIT_HBAR, deposit_hbar
+        )));
+    }
+
+    // Convert HBAR to tinybars (1 HBAR = 100_000_000 tinybars)
+    let deposit_tinybars = (deposit_hbar * 100_000_000.0) as u64;
+
     // Check if this is a libp2p peer ID (12D3KooW...)
     if peer_id_str.starts_with("12D3KooW") {
         // Parse libp2p peer ID
@@ -94,7 +125,15 @@ pub async fn close_channel(
     peer_id_str: &str,
 ) -> CliResult<String> {
     // Parse peer ID from hex string
-    let peer_id = parse_peer_id(peer_id_str)?;
+    parse_peer_id(peer_id_str)?;
+
+    // Forward to the running node if there is one
+    let request = IpcRequest::CloseChannel {
+        peer_id: peer_id_str.to_string(),
+    };
+    if let Some(output) = try_daemon_request(&config.base_dir(), format, request).await? {
+        return Ok(output);
+    }
 
     // Initialize context with network
     let mut ctx = NodeContext::with_network(config).await?;
@@ -102,6 +141,17 @@ pub async fn close_channel(
     // Bootstrap to connect to the network
     ctx.bootstrap().await?;
 
+    close_channel_with_context(&mut ctx, format, peer_id_str).await
+}
+
+/// Close a payment channel using an existing node context.
+pub async fn close_channel_with_context(
+    ctx: &mut NodeContext,
+    format: OutputFormat,
+    peer_id_str: &str,
+) -> CliResult<String> {
+    let peer_id = parse_peer_id(peer_id_str)?;
+
     // Get channel info before closing
     let channel = ctx
         .ops
@@ -188,7 +238,7 @@ pub async fn dispute_channel(
     let peer_id = parse_peer_id(peer_id_str)?;
 
     // Initialize context with network
-    let mut ctx = NodeContext::with_network(config).await?;
+    let ctx = NodeContext::with_network(config).await?;
 
     // Get channel info
     let channel = ctx
@@ -248,7 +298,7 @@ pub async fn resolve_dispute(
     let peer_id = parse_peer_id(peer_id_str)?;
 
     // Initialize context
-    let mut ctx = NodeContext::with_network(config).await?;
+    let ctx = NodeContext::with_network(config).await?;
 
     // Check dispute status first
     let status = ctx.ops.get_pending_dispute_status(&peer_id)?;
@@ -306,10 +356,22 @@ pub async fn resolve_dispute(
 }
 
 /// List all payment channels.
-pub fn list_channels(config: CliConfig, format: OutputFormat) -> CliResult<String> {
+pub async fn list_channels(config: CliConfig, format: OutputFormat) -> CliResult<String> {
+    // Forward to the running node if there is one
+    if let Some(output) =
+        try_daemon_request(&config.base_dir(), format, IpcRequest::ListChannels).await?
+    {
+        return Ok(output);
+    }
+
     // Use local context (no network needed for listing)
-    let ctx = NodeContext::local(config)?;
+    let ctx = NodeContext::local_read_only(config)?;
+
+    list_channels_with_context(&ctx, format)
+}
 
+/// List all payment channels using an existing node context.
+pub fn list_channels_with_context(ctx: &NodeContext, format: OutputFormat) -> CliResult<String> {
     // Get all open channels using list_open
     let channels = ctx.ops.state.channels.list_open()?;
 
@@ -337,12 +399,99 @@ pub fn list_channels(config: CliConfig, format: OutputFormat) -> CliResult<Strin
     Ok(output.render(format))
 }
 
+/// Plan and, unless `dry_run`, perform a rebalance of skewed channels.
+pub async fn rebalance_channels(
+    config: CliConfig,
+    format: OutputFormat,
+    dry_run: bool,
+) -> CliResult<String> {
+    // Forward to the running node if there is one
+    let request = IpcRequest::RebalanceChannels { dry_run };
+    if let Some(output) = try_daemon_request(&config.base_dir(), format, request).await? {
+        return Ok(output);
+    }
+
+    // A dry run only reads local channel state
+    let mut ctx = if dry_run {
+        NodeContext::local_read_only(config)?
+    } else {
+        let ctx = NodeContext::with_network(config).await?;
+        ctx.bootstrap().await?;
+        ctx
+    };
+
+    rebalance_channels_with_context(&mut ctx, format, dry_run).await
+}
+
+/// Rebalance payment channels using an existing node context.
+pub async fn rebalance_channels_with_context(
+    ctx: &mut NodeContext,
+    format: OutputFormat,
+    dry_run: bool,
+) -> CliResult<String> {
+    let plan = ctx.ops.plan_rebalance()?;
+
+    let outcomes: Vec<RebalanceOutcome> = if dry_run {
+        plan.actions
+            .iter()
+            .map(|action| RebalanceOutcome {
+                action: action.clone(),
+                performed: false,
+                reason: None,
+            })
+            .collect()
+    } else {
+        ctx.ops.execute_rebalance(&plan, &ctx.private_key).await?
+    };
+
+    let skew_summary = |skew: &ChannelSkew| ChannelSkewSummary {
+        peer_id: skew.peer.to_string(),
+        my_balance: skew.my_balance,
+        their_balance: skew.their_balance,
+        outbound_ratio: skew.outbound_ratio,
+    };
+
+    let output = RebalanceOutput {
+        dry_run,
+        saturated: plan.saturated.iter().map(skew_summary).collect(),
+        depleted: plan.depleted.iter().map(skew_summary).collect(),
+        actions: outcomes
+            .into_iter()
+            .map(|outcome| {
+                let (kind, from, to, peer_id, amount) = match outcome.action {
+                    RebalanceAction::Circular { from, to, amount } => (
+                        "circular",
+                        Some(from.to_string()),
+                        Some(to.to_string()),
+                        None,
+                        amount,
+                    ),
+                    RebalanceAction::Reopen { peer, deposit } => {
+                        ("reopen", None, None, Some(peer.to_string()), deposit)
+                    }
+                };
+                RebalanceActionSummary {
+                    kind: kind.to_string(),
+                    from,
+                    to,
+                    peer_id,
+                    amount,
+                    performed: outcome.performed,
+                    reason: outcome.reason,
+                }
+            })
+            .collect(),
+    };
+
+    Ok(output.render(format))
+}
+
 /// Parse a peer ID from a string.
 ///
 /// Accepts two formats:
 /// - Base58 format: `ndl1...` (human-readable, e.g., `ndl13zE3otwfgopSgkT17R3yfhcT3sj8`)
 /// - Hex format: 40 hex characters (e.g., `0102030405060708090a0b0c0d0e0f1011121314`)
-fn parse_peer_id(s: &str) -> CliResult<PeerId> {
+pub(crate) fn parse_peer_id(s: &str) -> CliResult<PeerId> {
     // Try base58 format first (starts with "ndl1")
     if s.starts_with("ndl1") {
         return nodalync_crypto::peer_id_from_string(s)
@@ -1,15 +1,15 @@
 //! Delete local content command.
 
-use nodalync_store::{ContentStore, ManifestStore};
-use nodalync_types::Visibility;
-
 use crate::config::CliConfig;
 use crate::context::{parse_hash, NodeContext};
 use crate::error::{CliError, CliResult};
 use crate::output::{DeleteOutput, OutputFormat, Render};
 
 /// Execute the delete command.
-pub fn delete(
+///
+/// Takes the content offline and broadcasts a tombstone so peers drop
+/// their copies.
+pub async fn delete(
     config: CliConfig,
     format: OutputFormat,
     hash_str: &str,
@@ -18,8 +18,8 @@ pub fn delete(
     // Parse hash
     let hash = parse_hash(hash_str)?;
 
-    // Initialize context
-    let mut ctx = NodeContext::local(config)?;
+    // Initialize context with network (needed to broadcast the tombstone)
+    let ctx = NodeContext::with_network(config).await?;
 
     // Verify content exists
     let manifest = ctx
@@ -48,11 +48,8 @@ pub fn delete(
         }
     }
 
-    // Take content offline (manifest preserved for provenance)
-    ctx.ops.state.content.delete(&hash)?;
-    let mut updated = manifest.clone();
-    updated.visibility = Visibility::Offline;
-    ctx.ops.state.manifests.update(&updated)?;
+    // Take content offline (manifest preserved for provenance) and withdraw it
+    ctx.ops.delete_content(&hash).await?;
 
     let output = DeleteOutput {
         hash: hash.to_string(),
@@ -76,8 +73,8 @@ mod tests {
         config
     }
 
-    #[test]
-    fn test_delete_not_found() {
+    #[tokio::test]
+    async fn test_delete_not_found() {
         std::env::set_var("NODALYNC_PASSWORD", "test_password");
 
         let temp_dir = TempDir::new().unwrap();
@@ -85,7 +82,7 @@ mod tests {
 
         init(config.clone(), OutputFormat::Human, false).unwrap();
 
-        let result = delete(config, OutputFormat::Human, "invalidhash", true);
+        let result = delete(config, OutputFormat::Human, "invalidhash", true).await;
         assert!(result.is_err());
     }
 }
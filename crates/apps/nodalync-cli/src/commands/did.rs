@@ -0,0 +1,148 @@
+//! DID export, resolution and verification commands.
+
+use std::path::Path;
+
+use nodalync_crypto::peer_id_to_string;
+use nodalync_ops::DidResolution;
+use nodalync_types::DidDocument;
+
+use crate::commands::channel::parse_peer_id;
+use crate::config::CliConfig;
+use crate::context::NodeContext;
+use crate::error::{CliError, CliResult};
+use crate::output::{DidOutput, DidResolutionOutput, OutputFormat, Render};
+
+/// Show our DID document, or write it to a file with `export`.
+pub fn did(config: CliConfig, format: OutputFormat, export: Option<&Path>) -> CliResult<String> {
+    let ctx = NodeContext::local_read_only(config)?;
+    let document = ctx.ops.did_document()?;
+
+    if let Some(path) = export {
+        std::fs::write(path, serde_json::to_string_pretty(&document)?)?;
+    }
+
+    let output = DidOutput {
+        did: document.id.clone(),
+        peer_id: ctx.peer_id().to_string(),
+        path: export.map(|path| path.display().to_string()),
+        document,
+    };
+    Ok(output.render(format))
+}
+
+/// Resolve another peer's DID to its document and PeerId.
+pub fn resolve_did(config: CliConfig, format: OutputFormat, did: &str) -> CliResult<String> {
+    let ctx = NodeContext::local_read_only(config)?;
+    let resolution = ctx.ops.resolve_did(did)?;
+    Ok(resolution_output(resolution, false).render(format))
+}
+
+/// Verify a DID document file, optionally against the peer it should
+/// belong to.
+pub fn verify_did(
+    config: CliConfig,
+    format: OutputFormat,
+    file: &Path,
+    peer: Option<&str>,
+) -> CliResult<String> {
+    if !file.exists() {
+        return Err(CliError::FileNotFound(file.display().to_string()));
+    }
+    let expected = peer.map(parse_peer_id).transpose()?;
+
+    let document: DidDocument = serde_json::from_str(&std::fs::read_to_string(file)?)
+        .map_err(|e| CliError::User(format!("Not a DID document: {}", e)))?;
+
+    let ctx = NodeContext::local_read_only(config)?;
+    let resolution = ctx.ops.verify_did_document(&document, expected.as_ref())?;
+    Ok(resolution_output(resolution, true).render(format))
+}
+
+fn resolution_output(resolution: DidResolution, verified: bool) -> DidResolutionOutput {
+    let peer = resolution.peer;
+    DidResolutionOutput {
+        verified,
+        did: resolution.document.id.clone(),
+        peer_id: peer_id_to_string(&resolution.peer_id),
+        public_key: format!("0x{}", hex::encode(resolution.public_key.0)),
+        known_peer: peer.is_some(),
+        addresses: peer
+            .as_ref()
+            .map(|p| p.addresses.clone())
+            .unwrap_or_default(),
+        reputation: peer.map(|p| p.reputation),
+        document: resolution.document,
+    }
+}
+
+// Simple hex encoding helper
+mod hex {
+    pub fn encode(bytes: impl AsRef<[u8]>) -> String {
+        bytes
+            .as_ref()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use nodalync_crypto::{did_from_public_key, generate_identity, peer_id_from_public_key};
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    #[test]
+    fn test_did_export_and_verify() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let result = did(config.clone(), OutputFormat::Human, None).unwrap();
+        assert!(result.contains("did:key:z6Mk"));
+
+        let file = temp_dir.path().join("did.json");
+        let result = did(config.clone(), OutputFormat::Json, Some(&file)).unwrap();
+        let exported: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let own_did = exported["did"].as_str().unwrap().to_string();
+        let own_peer = exported["peer_id"].as_str().unwrap().to_string();
+
+        let result =
+            verify_did(config.clone(), OutputFormat::Human, &file, Some(&own_peer)).unwrap();
+        assert!(result.contains("DID document verified"));
+        assert!(result.contains(&own_did));
+
+        // A document for another peer fails against our PeerId
+        let (_, other_key) = generate_identity();
+        std::fs::write(
+            &file,
+            serde_json::to_string(&DidDocument::new(&other_key)).unwrap(),
+        )
+        .unwrap();
+        assert!(verify_did(config.clone(), OutputFormat::Json, &file, Some(&own_peer)).is_err());
+
+        let result = resolve_did(
+            config.clone(),
+            OutputFormat::Json,
+            &did_from_public_key(&other_key),
+        )
+        .unwrap();
+        assert!(result.contains(&peer_id_to_string(&peer_id_from_public_key(&other_key))));
+        assert!(result.contains("\"known_peer\": false"));
+
+        assert!(resolve_did(config, OutputFormat::Json, "did:web:example.com").is_err());
+    }
+}
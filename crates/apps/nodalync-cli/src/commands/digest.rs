@@ -0,0 +1,134 @@
+//! Earnings and activity digest command.
+
+use nodalync_ops::{current_timestamp, last_complete_period};
+use nodalync_store::DigestPeriod;
+
+use crate::config::CliConfig;
+use crate::context::NodeContext;
+use crate::error::{CliError, CliResult};
+use crate::notifications::{build_notifiers, digest_subject, digest_text};
+use crate::output::{DigestOutput, NotifierFailure, OutputFormat, Render};
+
+/// Execute the digest command.
+///
+/// Builds the digest of the last full period. With `send`, it also goes
+/// through every notifier under `[notifications]`, which is how a running
+/// node sends it, so this checks the notifier settings too.
+pub async fn digest(
+    config: CliConfig,
+    format: OutputFormat,
+    period: DigestPeriod,
+    send: bool,
+) -> CliResult<String> {
+    let notifiers = if send {
+        let notifiers = build_notifiers(&config.notifications)?;
+        if notifiers.is_empty() {
+            return Err(CliError::config(
+                "No notifiers configured; add [notifications.email] or [[notifications.webhooks]]",
+            ));
+        }
+        notifiers
+    } else {
+        Vec::new()
+    };
+
+    let ctx = NodeContext::local_read_only(config)?;
+    let (start, end) = last_complete_period(period, current_timestamp());
+    let digest = ctx.ops.activity_digest(period, start, end)?;
+
+    let mut sent = Vec::new();
+    let mut failed = Vec::new();
+    for notifier in notifiers {
+        match notifier.notify(&digest).await {
+            Ok(()) => sent.push(notifier.name().to_string()),
+            Err(error) => failed.push(NotifierFailure {
+                notifier: notifier.name().to_string(),
+                error,
+            }),
+        }
+    }
+
+    let output = DigestOutput {
+        subject: digest_subject(&digest),
+        text: digest_text(&digest),
+        digest,
+        sent,
+        failed,
+    };
+
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use nodalync_crypto::content_hash;
+    use nodalync_store::{AccessKind, AccessLogStore, AccessRecord, AccessRequester};
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config
+    }
+
+    #[tokio::test]
+    async fn test_digest() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let human = digest(
+            config.clone(),
+            OutputFormat::Human,
+            DigestPeriod::Daily,
+            false,
+        )
+        .await
+        .unwrap();
+        assert!(human.contains("No activity this period"));
+
+        {
+            let ctx = NodeContext::local(config.clone()).unwrap();
+            let (start, _) = last_complete_period(DigestPeriod::Daily, current_timestamp());
+            ctx.ops
+                .state
+                .access_log
+                .record(&AccessRecord::new(
+                    content_hash(b"content"),
+                    AccessRequester::Hashed(content_hash(b"consumer")),
+                    AccessKind::Query,
+                    100_000_000,
+                    start + 1,
+                ))
+                .unwrap();
+        }
+
+        let json = digest(
+            config.clone(),
+            OutputFormat::Json,
+            DigestPeriod::Daily,
+            false,
+        )
+        .await
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["digest"]["earnings"], 100_000_000);
+        assert_eq!(value["digest"]["new_consumers"], 1);
+        assert!(value["subject"].as_str().unwrap().contains("daily digest"));
+        assert!(value.get("sent").is_none());
+
+        // Sending needs a notifier
+        assert!(
+            digest(config, OutputFormat::Json, DigestPeriod::Daily, true)
+                .await
+                .is_err()
+        );
+    }
+}
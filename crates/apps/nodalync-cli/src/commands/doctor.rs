@@ -0,0 +1,187 @@
+//! Node integrity check command.
+//!
+//! Reports the result of the last content scrub (run on a schedule by a
+//! running node, or on demand with `--scrub`) and any quarantined content.
+
+use std::path::{Path, PathBuf};
+
+use nodalync_ops::{ScrubOutcome, ScrubReport};
+use nodalync_store::ContentStore;
+
+use crate::config::CliConfig;
+use crate::context::NodeContext;
+use crate::error::{CliError, CliResult};
+use crate::ipc::{try_daemon_request, IpcRequest};
+use crate::output::{DoctorOutput, OutputFormat, Render, ScrubIssueSummary, ScrubSummary};
+
+/// Scrub report file name.
+const SCRUB_FILE_NAME: &str = "scrub.json";
+
+/// Execute the doctor command.
+pub async fn doctor(config: CliConfig, format: OutputFormat, scrub: bool) -> CliResult<String> {
+    // Forward to the running node if there is one
+    if let Some(output) =
+        try_daemon_request(&config.base_dir(), format, IpcRequest::Doctor { scrub }).await?
+    {
+        return Ok(output);
+    }
+
+    let mut ctx = if scrub {
+        // Quarantined content may be re-fetched from the network
+        let ctx = NodeContext::with_network(config).await?;
+        ctx.bootstrap().await?;
+        ctx
+    } else {
+        NodeContext::local_read_only(config)?
+    };
+
+    doctor_with_context(&mut ctx, format, scrub).await
+}
+
+/// Run the integrity check using an existing node context.
+pub async fn doctor_with_context(
+    ctx: &mut NodeContext,
+    format: OutputFormat,
+    scrub: bool,
+) -> CliResult<String> {
+    let path = scrub_report_path(&ctx.config.base_dir());
+
+    let last_scrub = if scrub {
+        let summary = run_scrub(ctx).await?;
+        write_scrub_report(&path, &summary)?;
+        Some(summary)
+    } else {
+        read_scrub_report(&path)
+    };
+
+    let output = DoctorOutput {
+        last_scrub,
+        scrub_interval_hours: ctx.config.storage.scrub_interval_hours,
+        quarantined: ctx
+            .ops
+            .state
+            .content
+            .quarantined()?
+            .iter()
+            .map(|h| h.to_string())
+            .collect(),
+    };
+
+    Ok(output.render(format))
+}
+
+/// Scrub stored content and summarize the result.
+pub async fn run_scrub(ctx: &mut NodeContext) -> CliResult<ScrubSummary> {
+    let report = ctx.ops.scrub_content().await?;
+    Ok(scrub_summary(&report))
+}
+
+/// Convert a scrub report into its persisted summary.
+pub fn scrub_summary(report: &ScrubReport) -> ScrubSummary {
+    ScrubSummary {
+        started_at: report.started_at,
+        finished_at: report.finished_at,
+        checked: report.checked,
+        issues: report
+            .issues
+            .iter()
+            .map(|issue| ScrubIssueSummary {
+                hash: issue.hash.to_string(),
+                title: issue.title.clone(),
+                outcome: match issue.outcome {
+                    ScrubOutcome::RestoredFromCache => "restored_from_cache",
+                    ScrubOutcome::Refetched => "refetched",
+                    ScrubOutcome::Quarantined => "quarantined",
+                }
+                .to_string(),
+            })
+            .collect(),
+    }
+}
+
+/// Get the scrub report path for the given base directory.
+pub fn scrub_report_path(base_dir: &Path) -> PathBuf {
+    base_dir.join(SCRUB_FILE_NAME)
+}
+
+/// Write the last scrub summary to the report file.
+pub fn write_scrub_report(path: &Path, summary: &ScrubSummary) -> CliResult<()> {
+    let json = serde_json::to_string(summary)
+        .map_err(|e| CliError::User(format!("Failed to serialize scrub report: {}", e)))?;
+    std::fs::write(path, json)?;
+    Ok(())
+}
+
+/// Read the last scrub summary.
+///
+/// Returns None if no scrub has run or the file can't be parsed.
+pub fn read_scrub_report(path: &Path) -> Option<ScrubSummary> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::content_hash;
+    use nodalync_ops::ScrubIssue;
+    use tempfile::TempDir;
+
+    fn sample_report() -> ScrubReport {
+        ScrubReport {
+            started_at: 1_000,
+            finished_at: 2_000,
+            checked: 3,
+            issues: vec![ScrubIssue {
+                hash: content_hash(b"rotten"),
+                title: "Rotten".to_string(),
+                outcome: ScrubOutcome::Quarantined,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_scrub_report_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = scrub_report_path(temp_dir.path());
+        assert!(read_scrub_report(&path).is_none());
+
+        let summary = scrub_summary(&sample_report());
+        write_scrub_report(&path, &summary).unwrap();
+
+        let read_back = read_scrub_report(&path).unwrap();
+        assert_eq!(read_back, summary);
+        assert_eq!(read_back.issues[0].outcome, "quarantined");
+    }
+
+    #[test]
+    fn test_doctor_output_render() {
+        let output = DoctorOutput {
+            last_scrub: Some(scrub_summary(&sample_report())),
+            scrub_interval_hours: 24,
+            quarantined: vec![content_hash(b"rotten").to_string()],
+        };
+
+        let human = output.render_human();
+        assert!(human.contains("3 blobs checked"));
+        assert!(human.contains("Rotten"));
+        assert!(human.contains("every 24h"));
+
+        let json = output.render_json();
+        assert!(json.contains("\"quarantined\""));
+    }
+
+    #[test]
+    fn test_doctor_output_never_scrubbed() {
+        let output = DoctorOutput {
+            last_scrub: None,
+            scrub_interval_hours: 0,
+            quarantined: Vec::new(),
+        };
+
+        let human = output.render_human();
+        assert!(human.contains("never"));
+        assert!(human.contains("disabled"));
+    }
+}
@@ -15,7 +15,7 @@ pub fn earnings(
     limit: u32,
 ) -> CliResult<String> {
     // Initialize context
-    let ctx = NodeContext::local(config)?;
+    let ctx = NodeContext::local_read_only(config)?;
 
     // Get all manifests (no filter to start)
     let filter = ManifestFilter::default();
@@ -0,0 +1,187 @@
+//! Group commands.
+
+use nodalync_crypto::Hash;
+
+use super::channel::parse_peer_id;
+use crate::config::CliConfig;
+use crate::context::{parse_hash, NodeContext};
+use crate::error::{CliError, CliResult};
+use crate::output::{group_to_summary, GroupListOutput, GroupOutput, OutputFormat, Render};
+use crate::prompt::get_identity_password;
+
+/// Create and sign a group owned by us.
+pub fn create_group(
+    config: CliConfig,
+    format: OutputFormat,
+    name: &str,
+    members: &[String],
+) -> CliResult<String> {
+    let members = members
+        .iter()
+        .map(|m| parse_peer_id(m))
+        .collect::<CliResult<Vec<_>>>()?;
+
+    let mut ctx = NodeContext::local(config)?;
+    load_signing_key(&mut ctx)?;
+
+    let group = ctx.ops.create_group(name, members)?;
+    group_output(&ctx, &group.id, "created", format)
+}
+
+/// Add and remove members of one of our groups.
+pub fn update_group(
+    config: CliConfig,
+    format: OutputFormat,
+    id_str: &str,
+    add: &[String],
+    remove: &[String],
+) -> CliResult<String> {
+    let id = parse_hash(id_str)?;
+    let add = add
+        .iter()
+        .map(|m| parse_peer_id(m))
+        .collect::<CliResult<Vec<_>>>()?;
+    let remove = remove
+        .iter()
+        .map(|m| parse_peer_id(m))
+        .collect::<CliResult<Vec<_>>>()?;
+
+    let mut ctx = NodeContext::local(config)?;
+    load_signing_key(&mut ctx)?;
+
+    ctx.ops.update_group(&id, &add, &remove)?;
+    group_output(&ctx, &id, "updated", format)
+}
+
+/// List groups we own or have cached.
+pub fn list_groups(config: CliConfig, format: OutputFormat) -> CliResult<String> {
+    let ctx = NodeContext::local_read_only(config)?;
+
+    let me = ctx.peer_id();
+    let groups: Vec<_> = ctx
+        .ops
+        .list_groups()?
+        .iter()
+        .map(|stored| group_to_summary(stored, &me))
+        .collect();
+
+    let output = GroupListOutput {
+        total: groups.len(),
+        groups,
+    };
+    Ok(output.render(format))
+}
+
+/// Fetch a group from its owner and cache it.
+pub async fn fetch_group(
+    config: CliConfig,
+    format: OutputFormat,
+    owner_str: &str,
+    id_str: &str,
+) -> CliResult<String> {
+    let owner = parse_peer_id(owner_str)?;
+    let id = parse_hash(id_str)?;
+
+    let ctx = NodeContext::with_network(config).await?;
+    ctx.bootstrap().await?;
+
+    ctx.ops.fetch_group(&owner, &id).await?;
+    group_output(&ctx, &id, "fetched", format)
+}
+
+/// Load the private key; group lists are signed by their owner.
+fn load_signing_key(ctx: &mut NodeContext) -> CliResult<()> {
+    let password = get_identity_password()?;
+    let (private_key, _) = ctx.ops.state.identity.load(&password).map_err(|e| {
+        if matches!(e, nodalync_store::StoreError::Encryption(_)) {
+            CliError::User(e.to_string())
+        } else {
+            CliError::from(e)
+        }
+    })?;
+    ctx.ops.set_private_key(private_key);
+    Ok(())
+}
+
+/// Render a stored group after an operation.
+fn group_output(
+    ctx: &NodeContext,
+    id: &Hash,
+    operation: &str,
+    format: OutputFormat,
+) -> CliResult<String> {
+    let stored = ctx
+        .ops
+        .get_group(id)?
+        .ok_or_else(|| CliError::User(format!("Group not found: {}", id)))?;
+    let output = GroupOutput {
+        operation: operation.to_string(),
+        group: group_to_summary(&stored, &ctx.peer_id()),
+    };
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key, peer_id_to_string};
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    fn test_peer() -> String {
+        let (_, public_key) = generate_identity();
+        peer_id_to_string(&peer_id_from_public_key(&public_key))
+    }
+
+    #[test]
+    fn test_create_update_and_list_groups() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let alice = test_peer();
+        let bob = test_peer();
+        let output = create_group(
+            config.clone(),
+            OutputFormat::Json,
+            "Reviewers",
+            std::slice::from_ref(&alice),
+        )
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["name"], "Reviewers");
+        assert_eq!(json["version"], 1);
+        assert!(json["owned"].as_bool().unwrap());
+        let id = json["id"].as_str().unwrap().to_string();
+
+        let output = update_group(
+            config.clone(),
+            OutputFormat::Json,
+            &id,
+            std::slice::from_ref(&bob),
+            std::slice::from_ref(&alice),
+        )
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["version"], 2);
+        assert_eq!(json["members"], serde_json::json!([bob]));
+
+        let output = list_groups(config.clone(), OutputFormat::Json).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["total"], 1);
+
+        assert!(create_group(config, OutputFormat::Human, "Other", &["bad".into()]).is_err());
+    }
+}
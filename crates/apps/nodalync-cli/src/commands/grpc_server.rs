@@ -0,0 +1,60 @@
+//! gRPC server command implementation.
+//!
+//! Serves the node's operations over gRPC for non-Rust integrations.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use nodalync_net::Network;
+use tracing::{info, warn};
+
+use crate::config::CliConfig;
+use crate::context::NodeContext;
+use crate::error::{CliError, CliResult};
+use crate::signals::shutdown_signal;
+
+/// Start the gRPC server.
+///
+/// Runs until SIGINT or SIGTERM. The node connects to the network when it
+/// is enabled in the config, so queries can reach peers and channel
+/// operations can be signed.
+pub async fn grpc_server(config: CliConfig, listen: SocketAddr) -> CliResult<String> {
+    let ctx = NodeContext::with_network(config).await?;
+    if let Err(e) = ctx.bootstrap().await {
+        warn!(
+            "Bootstrap failed: {} - continuing with limited connectivity",
+            e
+        );
+    }
+
+    // Share ops between RPCs and the network event processor
+    let ops = Arc::new(ctx.ops);
+    if let Some(network) = ctx.network {
+        let ops = Arc::clone(&ops);
+        tokio::spawn(async move {
+            loop {
+                match network.next_event().await {
+                    Ok(event) => {
+                        if let Err(e) = ops.handle_network_event(event).await {
+                            warn!("gRPC event handler error: {}", e);
+                        }
+                    }
+                    Err(e) => {
+                        warn!("gRPC network event error: {} - stopping event processor", e);
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    info!(listen = %listen, "Starting gRPC server");
+    let mut shutdown = shutdown_signal();
+    nodalync_grpc::serve(ops, listen, async move {
+        let _ = shutdown.changed().await;
+    })
+    .await
+    .map_err(|e| CliError::user(format!("gRPC server error: {}", e)))?;
+
+    Ok("gRPC server stopped.".to_string())
+}
@@ -0,0 +1,117 @@
+//! Import content from other networks.
+
+use nodalync_crypto::content_hash;
+use nodalync_types::Visibility;
+use nodalync_valid::IPFS_SCHEMA_URI;
+
+use super::publish::publish;
+use crate::config::CliConfig;
+use crate::error::{CliError, CliResult};
+use crate::ipfs::{extension_for, pointer_record, Cid, IpfsClient};
+use crate::output::{IpfsImportOutput, OutputFormat, Render};
+use crate::progress;
+
+/// Import an IPFS CID as L0 content.
+///
+/// The content is published like a file, with the CID recorded under the
+/// `nodalync:schema/ipfs/v1` schema. With `pin`, a record linking the
+/// Nodalync hash to the CID is added to the IPFS node and pinned first, so
+/// its CID is recorded too.
+#[allow(clippy::too_many_arguments)]
+pub async fn import_ipfs(
+    config: CliConfig,
+    format: OutputFormat,
+    cid: &str,
+    gateway: Option<String>,
+    price: Option<f64>,
+    visibility: Visibility,
+    title: Option<String>,
+    description: Option<String>,
+    tags: Vec<String>,
+    pin: bool,
+) -> CliResult<String> {
+    let cid = Cid::parse(cid)
+        .map_err(|e| CliError::InvalidInput(format!("Invalid CID {}: {}", cid, e)))?;
+
+    let mut ipfs_config = config.ipfs.clone();
+    if let Some(gateway) = gateway {
+        ipfs_config.gateway = gateway;
+    }
+    let client = IpfsClient::new(&ipfs_config);
+    if pin && !client.has_api() {
+        return Err(CliError::config(
+            "--pin needs an IPFS node: set api under [ipfs]",
+        ));
+    }
+
+    let spinner = if format == OutputFormat::Human {
+        progress::spinner("Fetching from IPFS...")
+    } else {
+        progress::hidden()
+    };
+    let fetched = client
+        .fetch(&cid)
+        .await
+        .map_err(|e| CliError::user(format!("Failed to fetch {}: {}", cid, e)))?;
+
+    let pinned = if pin {
+        spinner.set_message("Pinning record...");
+        let record = pointer_record(&content_hash(&fetched.data), &cid);
+        let pinned = client
+            .add_pinned(&format!("{}.json", cid), &record)
+            .await
+            .map_err(|e| CliError::user(format!("Failed to pin record: {}", e)))?;
+        Some(pinned)
+    } else {
+        None
+    };
+    spinner.finish_and_clear();
+
+    let mut fields = serde_json::json!({
+        "cid": cid.to_string(),
+        "source": fetched.source,
+        "verified": fetched.verified,
+    });
+    if let Some(ref pinned) = pinned {
+        fields["pin"] = serde_json::Value::String(pinned.clone());
+    }
+
+    // Publish reads a file, which a running node can read as well
+    let base_dir = config.base_dir();
+    std::fs::create_dir_all(&base_dir)?;
+    let file = base_dir.join(format!(
+        "import-{}.{}",
+        cid,
+        extension_for(fetched.content_type.as_deref())
+    ));
+    std::fs::write(&file, &fetched.data)?;
+
+    let published = publish(
+        config,
+        format,
+        &file,
+        price,
+        visibility,
+        Some(title.unwrap_or_else(|| cid.to_string())),
+        description,
+        None,
+        Some(IPFS_SCHEMA_URI.to_string()),
+        Some(fields.to_string()),
+        tags,
+    )
+    .await;
+    let _ = std::fs::remove_file(&file);
+    let published = published?;
+    if published == "Cancelled." {
+        return Ok(published);
+    }
+
+    Ok(IpfsImportOutput {
+        cid: cid.to_string(),
+        source: fetched.source,
+        verified: fetched.verified,
+        pin: pinned,
+        published,
+    }
+    .render(format))
+}
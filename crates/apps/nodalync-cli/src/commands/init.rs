@@ -1,5 +1,8 @@
 //! Initialize identity command.
 
+use nodalync_types::RevocationCertificate;
+use nodalync_valid::sign_revocation;
+
 use crate::config::{default_config_path, CliConfig};
 use crate::context::NodeContext;
 use crate::error::{CliError, CliResult};
@@ -49,6 +52,17 @@ pub fn init(config: CliConfig, format: OutputFormat, wizard: bool) -> CliResult<
     // Generate identity
     let peer_id = state.identity.generate(&password)?;
 
+    // Sign a revocation certificate now, while the key is known to be safe
+    let (private_key, public_key) = state.identity.load(&password)?;
+    let mut certificate =
+        RevocationCertificate::new(peer_id, public_key, nodalync_ops::current_timestamp());
+    sign_revocation(&private_key, &mut certificate);
+    let revocation_path = identity_dir.join("revocation.json");
+    std::fs::write(
+        &revocation_path,
+        serde_json::to_string_pretty(&certificate)?,
+    )?;
+
     // Save default config
     let config_path = default_config_path();
     config.save(&config_path)?;
@@ -57,6 +71,7 @@ pub fn init(config: CliConfig, format: OutputFormat, wizard: bool) -> CliResult<
     let output = InitOutput {
         peer_id: peer_id.to_string(),
         config_path: config_path.to_string_lossy().to_string(),
+        revocation_path: revocation_path.to_string_lossy().to_string(),
     };
 
     Ok(output.render(format))
@@ -91,6 +106,20 @@ mod tests {
         assert!(output.contains("Identity created"));
     }
 
+    #[test]
+    fn test_init_writes_revocation_certificate() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = test_config(&temp_dir);
+        init(config, OutputFormat::Human, false).unwrap();
+
+        let path = temp_dir.path().join("identity").join("revocation.json");
+        let certificate: RevocationCertificate =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert!(nodalync_valid::validate_revocation(&certificate).is_ok());
+    }
+
     #[test]
     fn test_init_fails_if_exists() {
         // Set password for non-interactive test
@@ -0,0 +1,217 @@
+//! Invoice commands.
+
+use nodalync_store::{InvoiceDirection, InvoiceStatus};
+
+use super::channel::parse_peer_id;
+use crate::config::{hbar_to_tinybars, CliConfig};
+use crate::context::{parse_hash, NodeContext};
+use crate::error::{CliError, CliResult};
+use crate::output::{invoice_to_summary, InvoiceListOutput, InvoiceOutput, OutputFormat, Render};
+use crate::prompt::get_identity_password;
+
+/// Create and sign an invoice for a payment to us.
+pub fn create_invoice(
+    config: CliConfig,
+    format: OutputFormat,
+    amount_hbar: f64,
+    memo: Option<String>,
+    reference: Option<&str>,
+    expires_in_hours: Option<u64>,
+) -> CliResult<String> {
+    let amount = hbar_to_tinybars(amount_hbar);
+    if amount == 0 {
+        return Err(CliError::User(
+            "Invoice amount must be greater than zero".into(),
+        ));
+    }
+    let reference = reference.map(parse_hash).transpose()?;
+
+    let mut ctx = NodeContext::local(config)?;
+
+    // Invoices are signed by the payee
+    let password = get_identity_password()?;
+    let (private_key, _) = ctx.ops.state.identity.load(&password).map_err(|e| {
+        if matches!(e, nodalync_store::StoreError::Encryption(_)) {
+            CliError::User(e.to_string())
+        } else {
+            CliError::from(e)
+        }
+    })?;
+    ctx.ops.set_private_key(private_key);
+
+    let invoice = ctx.ops.create_invoice(
+        amount,
+        memo.unwrap_or_default(),
+        reference,
+        expires_in_hours.map(|hours| hours.saturating_mul(3_600_000)),
+    )?;
+    let record = ctx
+        .ops
+        .get_invoice(&invoice.hash)?
+        .ok_or_else(|| CliError::User("Invoice was not stored".into()))?;
+
+    let output = InvoiceOutput {
+        operation: "created".to_string(),
+        invoice: invoice_to_summary(&record),
+    };
+    Ok(output.render(format))
+}
+
+/// List issued and received invoices.
+pub fn list_invoices(
+    config: CliConfig,
+    format: OutputFormat,
+    direction: Option<InvoiceDirection>,
+    unpaid: bool,
+) -> CliResult<String> {
+    let ctx = NodeContext::local_read_only(config)?;
+
+    let status = unpaid.then_some(InvoiceStatus::Unpaid);
+    let invoices: Vec<_> = ctx
+        .ops
+        .list_invoices(direction, status)?
+        .iter()
+        .map(invoice_to_summary)
+        .collect();
+
+    let output = InvoiceListOutput {
+        total: invoices.len(),
+        invoices,
+    };
+    Ok(output.render(format))
+}
+
+/// Send one of our invoices to the peer expected to pay it.
+pub async fn send_invoice(
+    config: CliConfig,
+    format: OutputFormat,
+    hash_str: &str,
+    peer_id_str: &str,
+) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+    let payer = parse_peer_id(peer_id_str)?;
+
+    let ctx = NodeContext::with_network(config).await?;
+    ctx.bootstrap().await?;
+
+    ctx.ops.send_invoice(&hash, &payer).await?;
+    invoice_output(&ctx, &hash, "sent", format)
+}
+
+/// Fetch an invoice from its payee.
+pub async fn fetch_invoice(
+    config: CliConfig,
+    format: OutputFormat,
+    peer_id_str: &str,
+    hash_str: &str,
+) -> CliResult<String> {
+    let payee = parse_peer_id(peer_id_str)?;
+    let hash = parse_hash(hash_str)?;
+
+    let ctx = NodeContext::with_network(config).await?;
+    ctx.bootstrap().await?;
+
+    ctx.ops.fetch_invoice(&payee, &hash).await?;
+    invoice_output(&ctx, &hash, "fetched", format)
+}
+
+/// Pay a received invoice through the payment channel with its payee.
+pub async fn pay_invoice(
+    config: CliConfig,
+    format: OutputFormat,
+    hash_str: &str,
+) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+
+    let ctx = NodeContext::with_network(config).await?;
+    ctx.bootstrap().await?;
+
+    let record = ctx.ops.pay_invoice(&hash).await?;
+    let output = InvoiceOutput {
+        operation: "paid".to_string(),
+        invoice: invoice_to_summary(&record),
+    };
+    Ok(output.render(format))
+}
+
+/// Render a tracked invoice after an operation.
+fn invoice_output(
+    ctx: &NodeContext,
+    hash: &nodalync_crypto::Hash,
+    operation: &str,
+    format: OutputFormat,
+) -> CliResult<String> {
+    let record = ctx
+        .ops
+        .get_invoice(hash)?
+        .ok_or_else(|| CliError::User(format!("Invoice not found: {}", hash)))?;
+    let output = InvoiceOutput {
+        operation: operation.to_string(),
+        invoice: invoice_to_summary(&record),
+    };
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config
+    }
+
+    #[test]
+    fn test_create_and_list_invoices() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let output = list_invoices(config.clone(), OutputFormat::Human, None, false).unwrap();
+        assert!(output.contains("No invoices"));
+
+        let output = create_invoice(
+            config.clone(),
+            OutputFormat::Json,
+            2.5,
+            Some("Consulting".to_string()),
+            None,
+            Some(24),
+        )
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["operation"], "created");
+        assert_eq!(json["amount"], 250_000_000);
+        assert_eq!(json["memo"], "Consulting");
+        assert_eq!(json["direction"], "issued");
+        assert_eq!(json["status"], "unpaid");
+        assert_eq!(
+            json["expires_at"].as_u64().unwrap() - json["created_at"].as_u64().unwrap(),
+            24 * 3_600_000
+        );
+
+        let output = list_invoices(
+            config.clone(),
+            OutputFormat::Json,
+            Some(InvoiceDirection::Received),
+            false,
+        )
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["total"], 0);
+
+        let output = list_invoices(config.clone(), OutputFormat::Json, None, true).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["total"], 1);
+
+        assert!(create_invoice(config, OutputFormat::Human, 0.0, None, None, None).is_err());
+    }
+}
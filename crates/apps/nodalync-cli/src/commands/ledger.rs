@@ -0,0 +1,153 @@
+//! Show or export the economic ledger command.
+
+use std::path::Path;
+
+use nodalync_store::LedgerAccount;
+
+use crate::config::CliConfig;
+use crate::context::NodeContext;
+use crate::error::CliResult;
+use crate::output::{
+    LedgerBalanceRow, LedgerEntryRow, LedgerExportOutput, LedgerOutput, OutputFormat,
+    ReconciliationRow, Render,
+};
+
+/// Execute the ledger command.
+///
+/// Shows the most recent `limit` entries (optionally for one account and
+/// from a time onwards), every account balance, and a reconciliation
+/// against open channels and the settlement queue. With `export`, writes
+/// the matching entries to a CSV file instead.
+pub async fn ledger(
+    config: CliConfig,
+    format: OutputFormat,
+    account: Option<LedgerAccount>,
+    since: Option<u64>,
+    limit: u32,
+    export: Option<&Path>,
+) -> CliResult<String> {
+    let ctx = NodeContext::local_read_only(config)?;
+
+    if let Some(path) = export {
+        let csv = ctx.ops.export_ledger_csv(since)?;
+        std::fs::write(path, &csv)?;
+        let output = LedgerExportOutput {
+            path: path.display().to_string(),
+            entries: csv.lines().count().saturating_sub(1),
+        };
+        return Ok(output.render(format));
+    }
+
+    let mut entries = ctx.ops.ledger_entries(account, since)?;
+    entries.drain(..entries.len().saturating_sub(limit as usize));
+
+    let reconciliation = ctx.ops.reconcile_ledger().await?;
+
+    let output = LedgerOutput {
+        account: account.map(|a| a.to_string()),
+        entries: entries
+            .into_iter()
+            .map(|e| LedgerEntryRow {
+                transaction_id: e.transaction_id,
+                timestamp: e.timestamp,
+                event: e.event.to_string(),
+                reference: e.reference,
+                account: e.account.to_string(),
+                debit: e.debit,
+                credit: e.credit,
+                balance: e.balance,
+            })
+            .collect(),
+        balances: ctx
+            .ops
+            .ledger_balances()?
+            .into_iter()
+            .map(|(account, balance)| LedgerBalanceRow {
+                account: account.to_string(),
+                balance,
+            })
+            .collect(),
+        total_debits: reconciliation.total_debits,
+        total_credits: reconciliation.total_credits,
+        reconciled: reconciliation.is_reconciled(),
+        checks: reconciliation
+            .checks
+            .iter()
+            .map(|c| ReconciliationRow {
+                account: c.account.to_string(),
+                ledger_balance: c.ledger_balance,
+                actual_balance: c.actual_balance,
+                difference: c.difference(),
+            })
+            .collect(),
+    };
+
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config
+    }
+
+    #[tokio::test]
+    async fn test_ledger_show_and_export() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let output = ledger(config.clone(), OutputFormat::Human, None, None, 20, None)
+            .await
+            .unwrap();
+        assert!(output.contains("No ledger entries yet"));
+
+        {
+            let ctx = NodeContext::local(config.clone()).unwrap();
+            let (_, public_key) = generate_identity();
+            let peer = peer_id_from_public_key(&public_key);
+            ctx.ops
+                .accept_payment_channel(&content_hash(b"channel"), &peer, 500, 300)
+                .unwrap();
+        }
+
+        let output = ledger(
+            config.clone(),
+            OutputFormat::Json,
+            Some(LedgerAccount::Channels),
+            None,
+            20,
+            None,
+        )
+        .await
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["account"], "channels");
+        assert_eq!(json["entries"][0]["event"], "channel_deposit");
+        assert_eq!(json["entries"][0]["balance"], 300);
+        assert_eq!(json["total_debits"], 300);
+        assert_eq!(json["checks"][0]["account"], "channels");
+        assert_eq!(json["checks"][0]["difference"], 0);
+
+        let path = temp_dir.path().join("ledger.csv");
+        let output = ledger(config, OutputFormat::Human, None, None, 20, Some(&path))
+            .await
+            .unwrap();
+        assert!(output.contains("2 ledger entries"));
+        let csv = std::fs::read_to_string(&path).unwrap();
+        assert!(csv.starts_with(nodalync_ops::LEDGER_CSV_HEADER));
+        assert_eq!(csv.lines().count(), 3);
+    }
+}
@@ -0,0 +1,199 @@
+//! Purchase library command.
+
+use std::path::PathBuf;
+
+use nodalync_ops::OpsError;
+
+use crate::config::CliConfig;
+use crate::context::{parse_hash, NodeContext};
+use crate::error::CliResult;
+use crate::output::{library_to_summary, LibraryOpenOutput, LibraryOutput, OutputFormat, Render};
+
+/// Execute the library command.
+///
+/// Lists the content we bought, only that whose title contains `search`
+/// if given. With `open`, re-opens that content instead and saves it to
+/// `output_path`, or to the cache directory. Content evicted from the cache
+/// is re-downloaded from its provider with the purchase receipt.
+pub async fn library(
+    config: CliConfig,
+    format: OutputFormat,
+    search: Option<&str>,
+    open: Option<&str>,
+    output_path: Option<PathBuf>,
+) -> CliResult<String> {
+    let open = open.map(parse_hash).transpose()?;
+    let ctx = NodeContext::local_read_only(config.clone())?;
+
+    if let Some(hash) = open {
+        let (entry, content, redownloaded) = match ctx.ops.open_from_library(&hash) {
+            Ok((entry, content)) => (entry, content, false),
+            Err(OpsError::InvalidOperation(_)) if config.network.enabled => {
+                drop(ctx);
+                let ctx = NodeContext::with_network(config.clone()).await?;
+                ctx.bootstrap().await?;
+                ctx.ops.redownload(&hash).await?;
+                let (entry, content) = ctx.ops.open_from_library(&hash)?;
+                (entry, content, true)
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let save_path = output_path.unwrap_or_else(|| {
+            let cache_dir = config.storage.cache_dir.clone();
+            std::fs::create_dir_all(&cache_dir).ok();
+            cache_dir.join(hash.to_string())
+        });
+        if let Some(parent) = save_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&save_path, content)?;
+
+        let output = LibraryOpenOutput {
+            entry: library_to_summary(&entry),
+            saved_to: save_path.display().to_string(),
+            redownloaded,
+        };
+        return Ok(output.render(format));
+    }
+
+    let entries = match search {
+        Some(text) => ctx.ops.search_library(text)?,
+        None => ctx.ops.library()?,
+    };
+    let output = LibraryOutput {
+        search: search.map(str::to_string),
+        total_paid: entries.iter().map(|e| e.price_paid).sum(),
+        entries: entries.iter().map(library_to_summary).collect(),
+    };
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key, Signature};
+    use nodalync_store::{CacheStore, CachedContent, LibraryEntry, LibraryStore};
+    use nodalync_types::ContentType;
+    use nodalync_wire::payload::PaymentReceipt;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_library() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let result = library(config.clone(), OutputFormat::Human, None, None, None)
+            .await
+            .unwrap();
+        assert!(result.contains("No purchases yet"));
+
+        // Record a purchase as a paid query would
+        let text = b"Field notes on owls.";
+        let hash = content_hash(text);
+        let (_, public_key) = generate_identity();
+        let provider = peer_id_from_public_key(&public_key);
+        let receipt = PaymentReceipt {
+            payment_id: content_hash(b"payment"),
+            amount: 25,
+            timestamp: 1_000,
+            channel_nonce: 1,
+            distributor_signature: Signature::from_bytes([0u8; 64]),
+            app_fee: 0,
+            app_fee_recipient: None,
+            referral_fee: 0,
+            referrer: None,
+        };
+        {
+            let ctx = NodeContext::local(config.clone()).unwrap();
+            ctx.ops
+                .state
+                .library
+                .record(&LibraryEntry {
+                    hash,
+                    title: "Owl Notes".to_string(),
+                    content_type: ContentType::L0,
+                    price_paid: 25,
+                    provider,
+                    purchased_at: 1_000,
+                    receipt: receipt.clone(),
+                })
+                .unwrap();
+            ctx.ops
+                .state
+                .cache
+                .cache(CachedContent::new(
+                    hash,
+                    text.to_vec(),
+                    provider,
+                    1_000,
+                    receipt,
+                ))
+                .unwrap();
+        }
+
+        let result = library(config.clone(), OutputFormat::Json, Some("owl"), None, None)
+            .await
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(value["entries"][0]["title"], "Owl Notes");
+        assert_eq!(value["entries"][0]["price_paid"], 25);
+        assert_eq!(value["total_paid"], 25);
+
+        let result = library(
+            config.clone(),
+            OutputFormat::Human,
+            Some("moss"),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(result.contains("No purchases matching"));
+
+        let out = temp_dir.path().join("owls.txt");
+        let hash_str = hash.to_string();
+        library(
+            config.clone(),
+            OutputFormat::Json,
+            None,
+            Some(&hash_str),
+            Some(out.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read(&out).unwrap(), text);
+
+        // Content that was never bought isn't in the library
+        let other = content_hash(b"never bought").to_string();
+        assert!(
+            library(config.clone(), OutputFormat::Json, None, Some(&other), None)
+                .await
+                .is_err()
+        );
+
+        // Evicted content needs the network to be re-downloaded
+        {
+            let ctx = NodeContext::local(config.clone()).unwrap();
+            ctx.ops.state.cache.remove(&hash).unwrap();
+        }
+        assert!(
+            library(config, OutputFormat::Json, None, Some(&hash_str), None)
+                .await
+                .is_err()
+        );
+    }
+}
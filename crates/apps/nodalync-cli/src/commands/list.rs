@@ -38,7 +38,7 @@ fn list_local(
     content_type_filter: Option<ContentType>,
     limit: u32,
 ) -> CliResult<String> {
-    let ctx = NodeContext::local(config)?;
+    let ctx = NodeContext::local_read_only(config)?;
 
     // Build filter
     let mut filter = ManifestFilter::default();
@@ -85,7 +85,7 @@ async fn list_network(
     };
 
     // Initialize context with networking enabled
-    let mut ctx = NodeContext::with_network(config).await?;
+    let ctx = NodeContext::with_network(config).await?;
 
     pb.set_message("Bootstrapping...");
     ctx.bootstrap().await?;
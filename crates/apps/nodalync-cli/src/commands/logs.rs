@@ -0,0 +1,134 @@
+//! Logs command.
+//!
+//! Reads the node's JSON log files back, optionally filtered by level and
+//! age, and can follow new entries as they are written.
+
+use std::str::FromStr;
+use std::time::Duration;
+
+use nodalync_crypto::current_timestamp;
+use tracing::Level;
+
+use crate::config::CliConfig;
+use crate::error::{CliError, CliResult};
+use crate::logging::{read_entries, LogEntry, LogFilter, LogFollower};
+use crate::output::{render_log_entry, LogsOutput, OutputFormat, Render};
+
+/// Interval between checks for new entries in follow mode.
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Execute the logs command.
+///
+/// Shows the last `lines` matching entries. With `follow`, keeps printing
+/// new matching entries until interrupted.
+pub async fn logs(
+    config: CliConfig,
+    format: OutputFormat,
+    follow: bool,
+    level: Option<String>,
+    since: Option<String>,
+    lines: usize,
+) -> CliResult<String> {
+    let base_dir = config.base_dir();
+    let filter = LogFilter {
+        min_level: level.as_deref().map(parse_level).transpose()?,
+        since_ms: since
+            .as_deref()
+            .map(parse_since)
+            .transpose()?
+            .map(|age| current_timestamp().saturating_sub(age.as_millis() as u64)),
+    };
+
+    // Start following before reading history so nothing is missed in between
+    let mut follower = LogFollower::new(&base_dir);
+    let entries = read_entries(&base_dir, config.logging.max_files, &filter, lines)?;
+
+    if !follow {
+        return Ok(LogsOutput { entries }.render(format));
+    }
+
+    for entry in &entries {
+        print_entry(entry, format);
+    }
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = tokio::time::sleep(FOLLOW_POLL_INTERVAL) => {
+                for entry in follower.poll()? {
+                    if filter.matches(&entry) {
+                        print_entry(&entry, format);
+                    }
+                }
+            }
+        }
+    }
+
+    // Everything has been printed already
+    Ok(String::new())
+}
+
+/// Print one entry as it arrives (JSON lines in JSON mode).
+fn print_entry(entry: &LogEntry, format: OutputFormat) {
+    match format {
+        OutputFormat::Human => println!("{}", render_log_entry(entry)),
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string(entry).unwrap_or_default())
+        }
+    }
+}
+
+/// Parse a level name (`error`, `warn`, `info`, `debug`, `trace`).
+fn parse_level(s: &str) -> CliResult<Level> {
+    Level::from_str(s).map_err(|_| {
+        CliError::user(format!(
+            "Invalid log level '{}'. Use error, warn, info, debug, or trace.",
+            s
+        ))
+    })
+}
+
+/// Parse a relative age like `30s`, `15m`, `1h`, or `2d`.
+fn parse_since(s: &str) -> CliResult<Duration> {
+    let invalid = || {
+        CliError::user(format!(
+            "Invalid --since value '{}'. Use a number with s, m, h, or d (e.g. 1h).",
+            s
+        ))
+    };
+    let s = s.trim();
+    let unit = s.chars().last().ok_or_else(invalid)?;
+    let number: u64 = s[..s.len() - unit.len_utf8()]
+        .parse()
+        .map_err(|_| invalid())?;
+    let secs = match unit {
+        's' => number,
+        'm' => number * 60,
+        'h' => number * 3600,
+        'd' => number * 86_400,
+        _ => return Err(invalid()),
+    };
+    Ok(Duration::from_secs(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!(parse_since("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_since("15m").unwrap(), Duration::from_secs(900));
+        assert_eq!(parse_since("1h").unwrap(), Duration::from_secs(3600));
+        assert_eq!(parse_since("2d").unwrap(), Duration::from_secs(172_800));
+        assert!(parse_since("").is_err());
+        assert!(parse_since("h").is_err());
+        assert!(parse_since("10x").is_err());
+    }
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(parse_level("warn").unwrap(), Level::WARN);
+        assert_eq!(parse_level("ERROR").unwrap(), Level::ERROR);
+        assert!(parse_level("loud").is_err());
+    }
+}
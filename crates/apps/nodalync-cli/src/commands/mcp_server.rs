@@ -5,6 +5,7 @@
 use std::path::PathBuf;
 
 use nodalync_mcp::server::{run_server, HederaConfig, McpServerConfig};
+use nodalync_ops::AutoOpenPolicy;
 use tracing::info;
 
 use crate::config::CliConfig;
@@ -28,6 +29,7 @@ pub async fn mcp_server(
     auto_approve: f64,
     enable_network: bool,
     hedera_args: HederaArgs,
+    tenant_key: Option<String>,
 ) -> CliResult<String> {
     // Build Hedera config if account ID is provided
     let hedera = if let Some(account_id) = hedera_args.account_id {
@@ -57,6 +59,7 @@ pub async fn mcp_server(
         auto_approve_hbar = auto_approve,
         enable_network = enable_network,
         hedera_enabled = hedera.is_some(),
+        tenant = tenant_key.is_some(),
         "Starting MCP server"
     );
 
@@ -68,6 +71,12 @@ pub async fn mcp_server(
         enable_network,
         bootstrap_nodes: config.network.bootstrap_nodes.clone(),
         hedera,
+        auto_open: AutoOpenPolicy::default(),
+        top_up: config.settlement.top_up_config(),
+        usage_reports: config.usage_reports.ops_config(),
+        moderation: config.moderation.ops_config(),
+        metrics: config.logging.otlp.metrics_config(),
+        tenant_key,
     };
 
     // Run the MCP server (this blocks until the server exits)
@@ -82,6 +91,8 @@ pub async fn mcp_server(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use nodalync_mcp::MetricsConfig;
+    use nodalync_ops::{ModerationConfig, TopUpConfig, UsageReportConfig};
 
     #[test]
     fn test_mcp_config_creation() {
@@ -93,6 +104,12 @@ mod tests {
             enable_network: false,
             bootstrap_nodes: vec![],
             hedera: None,
+            auto_open: AutoOpenPolicy::default(),
+            top_up: TopUpConfig::default(),
+            usage_reports: UsageReportConfig::default(),
+            moderation: ModerationConfig::default(),
+            metrics: MetricsConfig::default(),
+            tenant_key: None,
         };
 
         assert_eq!(config.budget_hbar, 1.0);
@@ -113,6 +130,12 @@ mod tests {
                     .to_string(),
             ],
             hedera: None,
+            auto_open: AutoOpenPolicy::default(),
+            top_up: TopUpConfig::default(),
+            usage_reports: UsageReportConfig::default(),
+            moderation: ModerationConfig::default(),
+            metrics: MetricsConfig::default(),
+            tenant_key: None,
         };
 
         assert!(config.enable_network);
@@ -133,6 +156,12 @@ mod tests {
                 contract_id: "0.0.7729011".to_string(),
                 network: "testnet".to_string(),
             }),
+            auto_open: AutoOpenPolicy::default(),
+            top_up: TopUpConfig::default(),
+            usage_reports: UsageReportConfig::default(),
+            moderation: ModerationConfig::default(),
+            metrics: MetricsConfig::default(),
+            tenant_key: None,
         };
 
         assert!(config.hedera.is_some());
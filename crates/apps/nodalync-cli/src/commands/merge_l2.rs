@@ -27,7 +27,7 @@ pub fn merge_l2(
     }
 
     // Initialize context
-    let mut ctx = NodeContext::local(config)?;
+    let ctx = NodeContext::local(config)?;
 
     // Verify graphs exist and are L2
     for graph in &graphs {
@@ -0,0 +1,117 @@
+//! Metered delivery command.
+
+use crate::config::CliConfig;
+use crate::context::{parse_hash, NodeContext};
+use crate::error::{CliError, CliResult};
+use crate::output::{MeteringOutput, OutputFormat, Render};
+
+/// Execute the metering command.
+///
+/// Meters the content in units of `unit_kb` KiB when given, stops
+/// metering it with `off`, and otherwise shows the current units.
+pub fn metering(
+    config: CliConfig,
+    format: OutputFormat,
+    hash_str: &str,
+    unit_kb: Option<u64>,
+    off: bool,
+) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+    let ctx = NodeContext::local(config)?;
+
+    let manifest = ctx
+        .ops
+        .get_content_manifest(&hash)?
+        .ok_or_else(|| CliError::NotFound(hash_str.to_string()))?;
+    if manifest.owner != ctx.peer_id() {
+        return Err(CliError::User("You don't own this content".to_string()));
+    }
+
+    if off {
+        ctx.ops.set_content_metering(&hash, None)?;
+    } else if let Some(unit_kb) = unit_kb {
+        ctx.ops
+            .set_content_metering(&hash, Some(unit_kb.saturating_mul(1_024)))?;
+    }
+
+    let manifest = ctx
+        .ops
+        .get_content_manifest(&hash)?
+        .ok_or_else(|| CliError::NotFound(hash_str.to_string()))?;
+
+    let output = MeteringOutput {
+        hash: hash.to_string(),
+        title: manifest.metadata.title.clone(),
+        price: manifest.economics.price,
+        unit_size: manifest.metadata.metering.as_ref().map(|t| t.chunk_size),
+        units: manifest.metadata.metered_units(),
+        unit_price: manifest.unit_price(0).unwrap_or(manifest.economics.price),
+    };
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use crate::commands::publish::publish;
+    use nodalync_crypto::content_hash;
+    use nodalync_types::Visibility;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_metering() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let text = "log line\n".repeat(500);
+        let file = temp_dir.path().join("log.txt");
+        std::fs::write(&file, &text).unwrap();
+        publish(
+            config.clone(),
+            OutputFormat::Json,
+            &file,
+            Some(1.0),
+            Visibility::Private,
+            Some("Log".to_string()),
+            None,
+            None,
+            None,
+            None,
+            vec![],
+        )
+        .await
+        .unwrap();
+        let hash = content_hash(text.as_bytes()).to_string();
+
+        let result = metering(config.clone(), OutputFormat::Human, &hash, None, false).unwrap();
+        assert!(result.contains("Sold whole only"));
+
+        let result = metering(config.clone(), OutputFormat::Json, &hash, Some(2), false).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(value["unit_size"], 2_048);
+        assert_eq!(value["units"], 3);
+        assert_eq!(value["unit_price"], 33_333_333);
+
+        // A single unit is no metering at all
+        assert!(metering(config.clone(), OutputFormat::Json, &hash, Some(8), false).is_err());
+
+        let result = metering(config, OutputFormat::Json, &hash, None, true).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(value.get("unit_size").is_none());
+        assert_eq!(value["units"], 0);
+    }
+}
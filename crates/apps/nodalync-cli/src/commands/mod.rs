@@ -1,56 +1,136 @@
 //! CLI command implementations.
 
+pub mod attribution;
+pub mod availability;
 pub mod balance;
+pub mod bond;
 pub mod build_l2;
 pub mod channel;
 pub mod completions;
 pub mod delete;
 pub mod deposit;
+pub mod did;
+pub mod digest;
+pub mod doctor;
 pub mod earnings;
+pub mod group;
+pub mod grpc_server;
+pub mod import;
 pub mod init;
+pub mod invoice;
+pub mod ledger;
+pub mod library;
 pub mod list;
+pub mod logs;
 pub mod mcp_server;
 pub mod merge_l2;
+pub mod metering;
+pub mod moderation;
+pub mod net;
+pub mod peers;
 pub mod preview;
+pub mod preview_policy;
+pub mod pricing;
 pub mod publish;
 pub mod query;
+pub mod quota;
 pub mod reference;
+pub mod referral;
+pub mod replay;
+pub mod replica;
+pub mod retention;
+pub mod revoke;
 pub mod search;
+pub mod sections;
 pub mod settle;
+pub mod share;
+pub mod simulate;
+pub mod standing_orders;
 pub mod start;
 pub mod status;
 pub mod stop;
+pub mod storefront;
+pub mod sync;
 pub mod synthesize;
+pub mod tags;
+pub mod tenants;
+pub mod trial;
 pub mod update;
+pub mod usage;
 pub mod versions;
 pub mod visibility;
+pub mod webhooks;
 pub mod whoami;
 pub mod withdraw;
 
 // Re-export command handlers
+pub use attribution::{attribution, endorse_attribution, verify_attribution};
+pub use availability::list_availability;
 pub use balance::balance;
+pub use bond::{bond, post_bond};
 pub use build_l2::build_l2;
-pub use channel::{close_channel, dispute_channel, list_channels, open_channel, resolve_dispute};
+pub use channel::{
+    close_channel, dispute_channel, list_channels, open_channel, rebalance_channels,
+    resolve_dispute,
+};
 pub use completions::completions;
 pub use delete::delete;
 pub use deposit::deposit;
+pub use did::{did, resolve_did, verify_did};
+pub use digest::digest;
+pub use doctor::doctor;
 pub use earnings::earnings;
+pub use group::{create_group, fetch_group, list_groups, update_group};
+pub use grpc_server::grpc_server;
+pub use import::import_ipfs;
 pub use init::init;
+pub use invoice::{create_invoice, fetch_invoice, list_invoices, pay_invoice, send_invoice};
+pub use ledger::ledger;
+pub use library::library;
 pub use list::list;
+pub use logs::logs;
 pub use mcp_server::mcp_server;
 pub use merge_l2::merge_l2;
+pub use metering::metering;
+pub use moderation::{allow, hide, moderation_queue, report, reports};
+pub use net::inspect_capture;
+pub use peers::list_peers;
 pub use preview::preview;
-pub use publish::publish;
+pub use preview_policy::preview_policy;
+pub use pricing::{pricing, pricing_rule};
+pub use publish::{publish, suggest_price};
 pub use query::query;
+pub use quota::quota;
 pub use reference::reference;
+pub use referral::referral;
+pub use replay::replay;
+pub use replica::{export_replica, import_replica, list_replicas};
+pub use retention::retention_status;
+pub use revoke::revoke;
 pub use search::search;
+pub use sections::sections;
 pub use settle::settle;
+pub use share::share;
+pub use simulate::simulate;
+pub use standing_orders::{
+    add_standing_order, list_standing_orders, remove_standing_order, standing_order_purchases,
+};
 pub use start::{start, start_daemon_sync};
 pub use status::status;
 pub use stop::stop;
+pub use storefront::storefront;
+pub use sync::{export_sync, import_sync, pull_sync, push_sync};
 pub use synthesize::synthesize;
+pub use tags::tags;
+pub use tenants::{
+    assign_tenant_content, create_tenant, list_tenants, remove_tenant, rotate_tenant_key,
+    set_tenant_budget, show_tenant, tenant_purchases,
+};
+pub use trial::trial;
 pub use update::update;
+pub use usage::{report_usage, stats};
 pub use versions::versions;
 pub use visibility::visibility;
+pub use webhooks::list_webhooks;
 pub use whoami::whoami;
 pub use withdraw::withdraw;
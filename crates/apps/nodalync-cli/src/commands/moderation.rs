@@ -0,0 +1,201 @@
+//! Content report and moderation commands.
+
+use nodalync_types::ReportReason;
+
+use crate::config::CliConfig;
+use crate::context::{parse_hash, NodeContext};
+use crate::error::{CliError, CliResult};
+use crate::output::{
+    moderation_to_summary, ContentReportsOutput, ModerationOutput, ModerationQueueOutput,
+    OutputFormat, Render, ReportOutput, ReportSummary,
+};
+
+/// Sign and broadcast a report about a piece of content.
+pub async fn report(
+    config: CliConfig,
+    format: OutputFormat,
+    hash_str: &str,
+    reason: ReportReason,
+    comment: &str,
+) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+    if !config.network.enabled {
+        return Err(CliError::User(
+            "Reports are broadcast to the network; enable networking in the config".into(),
+        ));
+    }
+
+    let ctx = NodeContext::with_network(config).await?;
+    ctx.bootstrap().await?;
+
+    let report = ctx.ops.report_content(&hash, reason, comment).await?;
+
+    let output = ReportOutput {
+        hash: hash.to_string(),
+        reason: report.reason.to_string(),
+        comment: report.comment,
+    };
+    Ok(output.render(format))
+}
+
+/// List reported content awaiting review, or everything moderated with
+/// `all`.
+pub fn moderation_queue(config: CliConfig, format: OutputFormat, all: bool) -> CliResult<String> {
+    let ctx = NodeContext::local_read_only(config)?;
+
+    let entries = if all {
+        ctx.ops.list_moderation(None)?
+    } else {
+        ctx.ops.moderation_queue()?
+    };
+    let entries: Vec<_> = entries.iter().map(moderation_to_summary).collect();
+
+    let output = ModerationQueueOutput {
+        total: entries.len(),
+        entries,
+    };
+    Ok(output.render(format))
+}
+
+/// Show the reports received for a piece of content.
+pub fn reports(config: CliConfig, format: OutputFormat, hash_str: &str) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+    let ctx = NodeContext::local_read_only(config)?;
+
+    let reports = ctx
+        .ops
+        .get_reports(&hash)?
+        .into_iter()
+        .map(|report| ReportSummary {
+            reporter: nodalync_crypto::peer_id_to_string(&report.reporter),
+            reason: report.reason.to_string(),
+            comment: report.comment,
+            created_at: report.created_at,
+        })
+        .collect();
+
+    let output = ContentReportsOutput {
+        hash: hash.to_string(),
+        status: ctx
+            .ops
+            .get_moderation_status(&hash)?
+            .map(|status| status.to_string()),
+        reports,
+    };
+    Ok(output.render(format))
+}
+
+/// Hide content from search results on this node.
+pub fn hide(config: CliConfig, format: OutputFormat, hash_str: &str) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+    let ctx = NodeContext::local(config)?;
+
+    ctx.ops.hide_content(&hash)?;
+    moderation_output(&ctx, &hash, format)
+}
+
+/// Keep reported content visible, dismissing its reports.
+pub fn allow(config: CliConfig, format: OutputFormat, hash_str: &str) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+    let ctx = NodeContext::local(config)?;
+
+    ctx.ops.allow_content(&hash)?;
+    moderation_output(&ctx, &hash, format)
+}
+
+/// Render the moderation status after a decision.
+fn moderation_output(
+    ctx: &NodeContext,
+    hash: &nodalync_crypto::Hash,
+    format: OutputFormat,
+) -> CliResult<String> {
+    let status = ctx
+        .ops
+        .get_moderation_status(hash)?
+        .map(|status| status.to_string())
+        .unwrap_or_default();
+    let output = ModerationOutput {
+        hash: hash.to_string(),
+        status,
+    };
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use crate::commands::publish::publish;
+    use nodalync_crypto::content_hash;
+    use nodalync_types::Visibility;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_hide_allow_and_queue() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let hash = content_hash(b"someone else's spam").to_string();
+        let result = moderation_queue(config.clone(), OutputFormat::Human, false).unwrap();
+        assert!(result.contains("No reported content"));
+
+        let result = hide(config.clone(), OutputFormat::Json, &hash).unwrap();
+        assert!(result.contains("\"status\": \"hidden\""));
+        // Decided content is no longer queued for review
+        let result = moderation_queue(config.clone(), OutputFormat::Json, false).unwrap();
+        assert!(result.contains("\"total\": 0"));
+        let result = moderation_queue(config.clone(), OutputFormat::Json, true).unwrap();
+        assert!(result.contains("\"total\": 1"));
+
+        let result = allow(config.clone(), OutputFormat::Human, &hash).unwrap();
+        assert!(result.contains("Allowed"));
+        let result = reports(config.clone(), OutputFormat::Json, &hash).unwrap();
+        assert!(result.contains("\"status\": \"allowed\""));
+        assert!(result.contains("\"reports\": []"));
+
+        // Our own content can't be hidden
+        let file = temp_dir.path().join("notes.txt");
+        std::fs::write(&file, "My notes").unwrap();
+        publish(
+            config.clone(),
+            OutputFormat::Json,
+            &file,
+            Some(0.0),
+            Visibility::Private,
+            Some("Notes".to_string()),
+            None,
+            None,
+            None,
+            None,
+            vec![],
+        )
+        .await
+        .unwrap();
+        let own = content_hash(b"My notes").to_string();
+        assert!(hide(config.clone(), OutputFormat::Human, &own).is_err());
+
+        // Reports need the network
+        let result = report(
+            config,
+            OutputFormat::Human,
+            &hash,
+            ReportReason::Spam,
+            "Link farm",
+        )
+        .await;
+        assert!(matches!(result, Err(CliError::User(_))));
+    }
+}
@@ -0,0 +1,186 @@
+//! Network diagnostics commands.
+//!
+//! `net inspect` summarizes a session capture (written by a node with
+//! `[network] capture = true`): per session, the message types seen, the
+//! error rate, and the payments sent and received, overall and per peer.
+
+use std::path::Path;
+
+use nodalync_net::{read_capture, PaymentFlow};
+
+use crate::error::{CliError, CliResult};
+use crate::output::{
+    CapturePeerOutput, CaptureSessionOutput, ErrorCodeCountOutput, MessageTypeCountOutput,
+    NetInspectOutput, OutputFormat, PaymentFlowOutput, Render,
+};
+
+/// Execute the net inspect command.
+pub fn inspect_capture(format: OutputFormat, file: &Path) -> CliResult<String> {
+    if !file.exists() {
+        return Err(CliError::FileNotFound(file.display().to_string()));
+    }
+
+    let sessions = read_capture(file)?
+        .iter()
+        .map(|session| {
+            let summary = session.summary();
+            let mut message_types: Vec<MessageTypeCountOutput> = summary
+                .message_types
+                .iter()
+                .map(|(name, count)| MessageTypeCountOutput {
+                    message_type: name.clone(),
+                    inbound: count.inbound,
+                    outbound: count.outbound,
+                })
+                .collect();
+            message_types.sort_by_key(|t| std::cmp::Reverse(t.inbound + t.outbound));
+
+            CaptureSessionOutput {
+                started_at: summary.started_at,
+                ended_at: summary.ended_at,
+                local_peer: summary.local_peer.map(|p| p.to_string()),
+                inbound: summary.messages.inbound,
+                outbound: summary.messages.outbound,
+                message_types,
+                undecodable: summary.undecodable,
+                error_codes: summary
+                    .error_codes
+                    .iter()
+                    .map(|(code, &count)| ErrorCodeCountOutput {
+                        code: code.clone(),
+                        count,
+                    })
+                    .collect(),
+                errors: summary.errors(),
+                error_rate: summary.error_rate(),
+                paid: flow_output(summary.paid),
+                received: flow_output(summary.received),
+                peers: summary
+                    .peers
+                    .iter()
+                    .map(|p| CapturePeerOutput {
+                        peer: p.peer.to_string(),
+                        inbound: p.messages.inbound,
+                        outbound: p.messages.outbound,
+                        errors: p.errors,
+                        paid: flow_output(p.paid),
+                        received: flow_output(p.received),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    let output = NetInspectOutput {
+        file: file.display().to_string(),
+        sessions,
+    };
+    Ok(output.render(format))
+}
+
+fn flow_output(flow: PaymentFlow) -> PaymentFlowOutput {
+    PaymentFlowOutput {
+        payments: flow.payments,
+        amount: flow.amount,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_net::{
+        CaptureConfig, CaptureRecord, ReplayDirection, ReplayKind, SessionRecorder,
+    };
+    use nodalync_types::ErrorCode;
+    use nodalync_wire::{
+        create_message, encode_message, encode_payload, MessageType, PingPayload, QueryErrorPayload,
+    };
+    use tempfile::TempDir;
+
+    fn message(message_type: MessageType, payload: Vec<u8>) -> Vec<u8> {
+        let (private_key, public_key) = generate_identity();
+        encode_message(&create_message(
+            message_type,
+            payload,
+            peer_id_from_public_key(&public_key),
+            1_000,
+            &private_key,
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_inspect_capture() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("capture.ndlcap");
+        let peer = nodalync_net::PeerId::random();
+        let ping = message(
+            MessageType::Ping,
+            encode_payload(&PingPayload { nonce: 1 }).unwrap(),
+        );
+        let error = message(
+            MessageType::QueryError,
+            encode_payload(&QueryErrorPayload::new(
+                content_hash(b"content"),
+                ErrorCode::NotFound,
+                "not found",
+            ))
+            .unwrap(),
+        );
+
+        let mut recorder = SessionRecorder::open(
+            &CaptureConfig::new(&path),
+            nodalync_net::PeerId::random(),
+            1_000,
+        )
+        .unwrap();
+        for record in [
+            CaptureRecord::new(ReplayDirection::Inbound, ReplayKind::Request, &ping, 2_000)
+                .with_peer(peer),
+            CaptureRecord::new(ReplayDirection::Inbound, ReplayKind::Request, &ping, 3_000)
+                .with_peer(peer),
+            CaptureRecord::new(
+                ReplayDirection::Outbound,
+                ReplayKind::Response,
+                &error,
+                4_000,
+            )
+            .with_peer(peer),
+            CaptureRecord::new(
+                ReplayDirection::Inbound,
+                ReplayKind::Broadcast,
+                b"junk",
+                5_000,
+            ),
+        ] {
+            recorder.record(&record).unwrap();
+        }
+        drop(recorder);
+
+        let human = inspect_capture(OutputFormat::Human, &path).unwrap();
+        assert!(human.contains("Session 1"));
+        assert!(human.contains("Messages: 4 (3 in, 1 out)"));
+        assert!(human.contains("2 errors (50.0%)"));
+        assert!(human.contains("Query errors: NOT_FOUND 1"));
+
+        let json = inspect_capture(OutputFormat::Json, &path).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let session = &value["sessions"][0];
+        assert_eq!(session["ended_at"], 5_000);
+        assert_eq!(session["message_types"][0]["message_type"], "PING");
+        assert_eq!(session["message_types"][0]["inbound"], 2);
+        assert_eq!(session["undecodable"], 1);
+        assert_eq!(session["error_rate"], 0.5);
+        assert_eq!(session["peers"][0]["peer"], peer.to_string());
+        assert_eq!(session["peers"][0]["outbound"], 1);
+        assert_eq!(session["peers"][0]["errors"], 1);
+
+        assert!(matches!(
+            inspect_capture(OutputFormat::Human, &temp_dir.path().join("missing.ndlcap")),
+            Err(CliError::FileNotFound(_))
+        ));
+        std::fs::write(temp_dir.path().join("bad.ndlcap"), b"nope").unwrap();
+        assert!(inspect_capture(OutputFormat::Human, &temp_dir.path().join("bad.ndlcap")).is_err());
+    }
+}
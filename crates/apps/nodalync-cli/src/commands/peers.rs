@@ -0,0 +1,128 @@
+//! Known peer commands.
+//!
+//! `peers list` shows the peers in the peer store with what the latency
+//! probes of a running node (`[qos]`) measured: round-trip percentiles,
+//! jitter, loss and the QoS score content providers are ranked by.
+
+use std::cmp::Ordering;
+
+use nodalync_crypto::peer_id_to_string;
+use nodalync_store::PeerStore;
+
+use crate::config::CliConfig;
+use crate::context::NodeContext;
+use crate::error::CliResult;
+use crate::output::{OutputFormat, PeerLatencyInfo, PeerListOutput, Render};
+
+/// Execute the peers list command.
+pub fn list_peers(config: CliConfig, format: OutputFormat) -> CliResult<String> {
+    let ctx = NodeContext::local_read_only(config)?;
+
+    let mut peers: Vec<PeerLatencyInfo> = ctx
+        .ops
+        .state
+        .peers
+        .list()?
+        .into_iter()
+        .map(|peer| {
+            let latency = peer.latency.as_ref();
+            PeerLatencyInfo {
+                peer_id: peer_id_to_string(&peer.peer_id),
+                reputation: peer.reputation,
+                last_seen: peer.last_seen,
+                probes: latency.map_or(0, |l| l.probes),
+                last_probe: latency.map(|l| l.last_probe),
+                p50_ms: latency.and_then(|l| l.percentile(50.0)),
+                p90_ms: latency.and_then(|l| l.percentile(90.0)),
+                p99_ms: latency.and_then(|l| l.percentile(99.0)),
+                jitter_ms: latency.and_then(|l| l.jitter_ms()),
+                loss_rate: latency.map(|l| l.loss_rate()),
+                qos_score: peer.qos_score(),
+            }
+        })
+        .collect();
+    // Best QoS first, unmeasured peers last
+    peers.sort_by(|a, b| match (a.qos_score, b.qos_score) {
+        (Some(a), Some(b)) => b.partial_cmp(&a).unwrap_or(Ordering::Equal),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    });
+
+    let output = PeerListOutput {
+        total: peers.len(),
+        peers,
+    };
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::PeerInfo;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    fn add_peer(ctx: &NodeContext) -> PeerInfo {
+        let (_, public_key) = generate_identity();
+        let peer = PeerInfo::new(
+            peer_id_from_public_key(&public_key),
+            public_key,
+            vec![],
+            1_000,
+        );
+        ctx.ops.state.peers.upsert(&peer).unwrap();
+        peer
+    }
+
+    #[test]
+    fn test_list_peers() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let output = list_peers(config.clone(), OutputFormat::Human).unwrap();
+        assert!(output.contains("No known peers"));
+
+        {
+            let ctx = NodeContext::local(config.clone()).unwrap();
+            add_peer(&ctx);
+            let measured = add_peer(&ctx);
+            for rtt in [Some(40), Some(60), None, Some(50)] {
+                ctx.ops
+                    .state
+                    .peers
+                    .record_latency(&measured.peer_id, rtt, 2_000)
+                    .unwrap();
+            }
+        }
+
+        let human = list_peers(config.clone(), OutputFormat::Human).unwrap();
+        assert!(human.contains("2 known peers"));
+        assert!(human.contains("p50 50ms  p90 60ms  p99 60ms"));
+        assert!(human.contains("loss 25.0%"));
+        assert!(human.contains("not probed"));
+
+        let json = list_peers(config, OutputFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["total"], 2);
+        let measured = &value["peers"][0];
+        assert_eq!(measured["probes"], 4);
+        assert_eq!(measured["p50_ms"], 50);
+        assert_eq!(measured["p99_ms"], 60);
+        assert_eq!(measured["jitter_ms"], 15.0);
+        assert_eq!(measured["loss_rate"], 0.25);
+        assert!(measured["qos_score"].as_f64().unwrap() > 0.0);
+        assert!(value["peers"][1]["qos_score"].is_null());
+    }
+}
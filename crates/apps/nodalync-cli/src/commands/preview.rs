@@ -3,19 +3,39 @@
 use crate::config::CliConfig;
 use crate::context::{parse_hash, NodeContext};
 use crate::error::{CliError, CliResult};
+use crate::ipc::{try_daemon_request, IpcRequest};
 use crate::output::{l1_to_preview, OutputFormat, PreviewOutput, Render};
 
 /// Execute the preview command.
 pub async fn preview(config: CliConfig, format: OutputFormat, hash_str: &str) -> CliResult<String> {
     // Parse hash
-    let hash = parse_hash(hash_str)?;
+    parse_hash(hash_str)?;
+
+    // Forward to the running node if there is one
+    let request = IpcRequest::Preview {
+        hash: hash_str.to_string(),
+    };
+    if let Some(output) = try_daemon_request(&config.base_dir(), format, request).await? {
+        return Ok(output);
+    }
 
     // Initialize context (try network first for remote content)
-    let mut ctx = NodeContext::with_network(config).await?;
+    let ctx = NodeContext::with_network(config).await?;
 
     // Bootstrap to find peers
     ctx.bootstrap().await?;
 
+    preview_with_context(&ctx, format, hash_str).await
+}
+
+/// Preview content using an existing node context.
+pub async fn preview_with_context(
+    ctx: &NodeContext,
+    format: OutputFormat,
+    hash_str: &str,
+) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+
     // Try to preview
     let preview_response = ctx
         .ops
@@ -37,6 +57,17 @@ pub async fn preview(config: CliConfig, format: OutputFormat, hash_str: &str) ->
         content_type: format!("{:?}", manifest.content_type),
         visibility: format!("{:?}", manifest.visibility),
         size: manifest.metadata.content_size,
+        schema: manifest.metadata.schema.clone(),
+        fields: manifest
+            .metadata
+            .fields
+            .as_deref()
+            .and_then(|f| serde_json::from_str(f).ok()),
+        forks: preview_response
+            .forks
+            .iter()
+            .map(|hash| hash.to_string())
+            .collect(),
         mentions,
     };
 
@@ -0,0 +1,144 @@
+//! Preview policy command.
+
+use nodalync_types::PreviewPolicy;
+
+use crate::config::CliConfig;
+use crate::context::{parse_hash, NodeContext};
+use crate::error::{CliError, CliResult};
+use crate::output::{OutputFormat, PreviewPolicyOutput, Render};
+
+/// Execute the preview-policy command.
+///
+/// Replaces the redaction rules when any are given, removes them with
+/// `clear`, and otherwise shows the current ones. Either way the output
+/// includes the summary peers see before paying.
+pub fn preview_policy(
+    config: CliConfig,
+    format: OutputFormat,
+    hash_str: &str,
+    patterns: &[String],
+    sections: &[String],
+    clear: bool,
+) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+    let ctx = NodeContext::local(config)?;
+
+    let manifest = ctx
+        .ops
+        .get_content_manifest(&hash)?
+        .ok_or_else(|| CliError::NotFound(hash_str.to_string()))?;
+    if manifest.owner != ctx.peer_id() {
+        return Err(CliError::User("You don't own this content".to_string()));
+    }
+
+    if clear {
+        ctx.ops.set_content_preview_policy(&hash, None)?;
+    } else if !patterns.is_empty() || !sections.is_empty() {
+        let policy = PreviewPolicy {
+            redact_patterns: patterns.to_vec(),
+            redact_sections: sections.to_vec(),
+        };
+        ctx.ops.set_content_preview_policy(&hash, Some(policy))?;
+    }
+
+    let policy = ctx
+        .ops
+        .get_content_manifest(&hash)?
+        .and_then(|m| m.metadata.preview_policy)
+        .unwrap_or_default();
+    let summary = ctx.ops.preview_summary(&hash)?;
+
+    let output = PreviewPolicyOutput {
+        hash: hash.to_string(),
+        redact_patterns: policy.redact_patterns,
+        redact_sections: policy.redact_sections,
+        summary: summary.summary,
+        preview_mentions: summary
+            .preview_mentions
+            .into_iter()
+            .map(|m| m.content)
+            .collect(),
+    };
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use crate::commands::publish::publish;
+    use nodalync_crypto::content_hash;
+    use nodalync_types::Visibility;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_preview_policy() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let text =
+            "# Notes\n\nThe public part is here.\n\n## Secret\n\nThe Falcon Project is hidden.\n";
+        let file = temp_dir.path().join("notes.md");
+        std::fs::write(&file, text).unwrap();
+        publish(
+            config.clone(),
+            OutputFormat::Json,
+            &file,
+            Some(0.0),
+            Visibility::Private,
+            Some("Notes".to_string()),
+            None,
+            None,
+            None,
+            None,
+            vec![],
+        )
+        .await
+        .unwrap();
+        let hash = content_hash(text.as_bytes()).to_string();
+
+        let result =
+            preview_policy(config.clone(), OutputFormat::Json, &hash, &[], &[], false).unwrap();
+        assert!(result.contains("Falcon"));
+
+        let result = preview_policy(
+            config.clone(),
+            OutputFormat::Json,
+            &hash,
+            &["public".to_string()],
+            &["secret".to_string()],
+            false,
+        )
+        .unwrap();
+        assert!(!result.contains("Falcon"));
+        assert!(result.contains("The [redacted] part is here."));
+
+        // Invalid patterns are rejected
+        let result = preview_policy(
+            config.clone(),
+            OutputFormat::Json,
+            &hash,
+            &["(unclosed".to_string()],
+            &[],
+            false,
+        );
+        assert!(result.is_err());
+
+        let result = preview_policy(config, OutputFormat::Human, &hash, &[], &[], true).unwrap();
+        assert!(result.contains("No preview policy"));
+        assert!(result.contains("Falcon"));
+    }
+}
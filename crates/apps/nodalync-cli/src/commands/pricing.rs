@@ -0,0 +1,180 @@
+//! Dynamic pricing command.
+
+use nodalync_ops::current_timestamp;
+use nodalync_types::PricingRule;
+
+use crate::config::{hbar_to_tinybars, CliConfig};
+use crate::context::{parse_hash, NodeContext};
+use crate::error::{CliError, CliResult};
+use crate::output::{OutputFormat, PricingOutput, Render};
+
+/// Surge window used when none is given, in minutes.
+pub const DEFAULT_SURGE_WINDOW_MINUTES: u64 = 60;
+
+/// Build a pricing rule from the command-line options, if any were given.
+///
+/// A Dutch auction starts now.
+pub fn pricing_rule(
+    surge_max: Option<f64>,
+    surge_step: Option<u32>,
+    window_minutes: Option<u64>,
+    decay_from: Option<f64>,
+    decay_hours: Option<f64>,
+) -> Option<PricingRule> {
+    if let (Some(max), Some(step)) = (surge_max, surge_step) {
+        let minutes = window_minutes.unwrap_or(DEFAULT_SURGE_WINDOW_MINUTES);
+        return Some(PricingRule::Surge {
+            max_price: hbar_to_tinybars(max),
+            window_ms: minutes * 60_000,
+            step_bps: step,
+        });
+    }
+    if let (Some(from), Some(hours)) = (decay_from, decay_hours) {
+        return Some(PricingRule::Decay {
+            start_price: hbar_to_tinybars(from),
+            starts_at: current_timestamp(),
+            duration_ms: (hours * 3_600_000.0) as u64,
+        });
+    }
+    None
+}
+
+/// Execute the pricing command.
+///
+/// Sets the rule when one is given, removes it with `fixed`, and otherwise
+/// shows the current one. Either way the output includes the price charged
+/// right now.
+pub fn pricing(
+    config: CliConfig,
+    format: OutputFormat,
+    hash_str: &str,
+    rule: Option<PricingRule>,
+    fixed: bool,
+) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+    let ctx = NodeContext::local(config)?;
+
+    let manifest = ctx
+        .ops
+        .get_content_manifest(&hash)?
+        .ok_or_else(|| CliError::NotFound(hash_str.to_string()))?;
+    if manifest.owner != ctx.peer_id() {
+        return Err(CliError::User("You don't own this content".to_string()));
+    }
+
+    if fixed {
+        ctx.ops.set_content_pricing(&hash, None)?;
+    } else if rule.is_some() {
+        ctx.ops.set_content_pricing(&hash, rule)?;
+    }
+
+    let manifest = ctx
+        .ops
+        .get_content_manifest(&hash)?
+        .ok_or_else(|| CliError::NotFound(hash_str.to_string()))?;
+    let now = current_timestamp();
+
+    let output = PricingOutput {
+        hash: hash.to_string(),
+        title: manifest.metadata.title.clone(),
+        base_price: manifest.economics.price,
+        rule: manifest.economics.pricing,
+        window_queries: ctx.ops.window_queries(&manifest, now)?,
+        current_price: ctx.ops.current_price(&manifest, now)?,
+    };
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use crate::commands::publish::publish;
+    use nodalync_crypto::content_hash;
+    use nodalync_types::Visibility;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    #[test]
+    fn test_pricing_rule() {
+        assert_eq!(pricing_rule(None, None, None, None, None), None);
+
+        let rule = pricing_rule(Some(2.0), Some(500), None, None, None).unwrap();
+        assert_eq!(
+            rule,
+            PricingRule::Surge {
+                max_price: 200_000_000,
+                window_ms: DEFAULT_SURGE_WINDOW_MINUTES * 60_000,
+                step_bps: 500,
+            }
+        );
+
+        match pricing_rule(None, None, None, Some(5.0), Some(1.5)).unwrap() {
+            PricingRule::Decay {
+                start_price,
+                duration_ms,
+                ..
+            } => {
+                assert_eq!(start_price, 500_000_000);
+                assert_eq!(duration_ms, 5_400_000);
+            }
+            rule => panic!("expected decay, got {:?}", rule),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pricing() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let text = "Live market data";
+        let file = temp_dir.path().join("feed.txt");
+        std::fs::write(&file, text).unwrap();
+        publish(
+            config.clone(),
+            OutputFormat::Json,
+            &file,
+            Some(1.0),
+            Visibility::Private,
+            Some("Feed".to_string()),
+            None,
+            None,
+            None,
+            None,
+            vec![],
+        )
+        .await
+        .unwrap();
+        let hash = content_hash(text.as_bytes()).to_string();
+
+        let result = pricing(config.clone(), OutputFormat::Human, &hash, None, false).unwrap();
+        assert!(result.contains("Fixed price"));
+
+        let rule = pricing_rule(None, None, None, Some(5.0), Some(1.0));
+        let result = pricing(config.clone(), OutputFormat::Json, &hash, rule, false).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(value["rule"]["mode"], "decay");
+        assert!(value["current_price"].as_u64().unwrap() > 100_000_000);
+
+        // The bounds must be above the base price
+        let rule = pricing_rule(Some(0.5), Some(100), None, None, None);
+        assert!(pricing(config.clone(), OutputFormat::Json, &hash, rule, false).is_err());
+
+        let result = pricing(config, OutputFormat::Json, &hash, None, true).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(value.get("rule").is_none());
+        assert_eq!(value["current_price"], 100_000_000);
+    }
+}
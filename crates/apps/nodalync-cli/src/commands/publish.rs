@@ -1,18 +1,26 @@
 //! Publish content command.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use colored::Colorize;
+use indicatif::ProgressBar;
 use nodalync_crypto::content_hash;
-use nodalync_types::{Metadata, Visibility};
+use nodalync_types::{ContentType, Metadata, Visibility};
 
 use crate::config::{ndl_to_units, tinybars_to_hbar, CliConfig};
 use crate::context::NodeContext;
 use crate::error::{CliError, CliResult};
-use crate::output::{OutputFormat, PublishOutput, Render};
+use crate::ipc::{absolute_path, try_daemon_request, IpcRequest};
+use crate::output::{OutputFormat, PriceSuggestionOutput, PublishOutput, Render};
 use crate::progress;
 
 /// Execute the publish command.
+///
+/// With `publish_at` (Unix ms), the content is stored and embargoed until
+/// then; the node announces it when the time comes. `fields` is a JSON
+/// object validated against the `schema` URI. `tags` are the author's tags;
+/// topics found by L1 extraction are added to them.
+#[allow(clippy::too_many_arguments)]
 pub async fn publish(
     config: CliConfig,
     format: OutputFormat,
@@ -21,6 +29,10 @@ pub async fn publish(
     visibility: Visibility,
     title: Option<String>,
     description: Option<String>,
+    publish_at: Option<u64>,
+    schema: Option<String>,
+    fields: Option<String>,
+    tags: Vec<String>,
 ) -> CliResult<String> {
     // Validate file exists
     if !file.exists() {
@@ -34,6 +46,22 @@ pub async fn publish(
         ));
     }
 
+    // Forward to the running node if there is one
+    let request = IpcRequest::Publish {
+        file: absolute_path(file)?,
+        price,
+        visibility,
+        title: title.clone(),
+        description: description.clone(),
+        publish_at,
+        schema: schema.clone(),
+        fields: fields.clone(),
+        tags: tags.clone(),
+    };
+    if let Some(output) = try_daemon_request(&config.base_dir(), format, request).await? {
+        return Ok(output);
+    }
+
     // Create spinner for human output
     let spinner = if format == OutputFormat::Human {
         progress::spinner("Reading file...")
@@ -41,6 +69,165 @@ pub async fn publish(
         progress::hidden()
     };
 
+    let prepared = match prepare_content(&config, format, file, price, title, schema, fields, tags)?
+    {
+        Some(prepared) => prepared,
+        None => return Ok("Cancelled.".to_string()),
+    };
+
+    // Initialize context with network
+    spinner.set_message("Connecting to network...");
+    let mut ctx = NodeContext::with_network(config).await?;
+
+    // Bootstrap the network to find peers
+    ctx.bootstrap().await?;
+
+    // Subscribe to announcements (required for GossipSub mesh formation)
+    if let Some(ref network) = ctx.network {
+        network.subscribe_announcements().await?;
+    }
+
+    let output = match publish_prepared(
+        &mut ctx,
+        format,
+        &prepared,
+        visibility,
+        description,
+        publish_at,
+        &spinner,
+    )
+    .await?
+    {
+        Some(output) => output,
+        None => return Ok("Cancelled.".to_string()),
+    };
+
+    // Wait for GossipSub propagation (needs time for mesh to form)
+    spinner.set_message("Propagating to network...");
+    let wait_secs = ctx.config.network.gossipsub_propagation_wait;
+    tokio::time::sleep(std::time::Duration::from_secs(wait_secs)).await;
+    spinner.finish_and_clear();
+
+    Ok(output.render(format))
+}
+
+/// Execute the publish command with `--suggest-price`.
+///
+/// Suggests a price range for the file from the announcements the node has
+/// stored and its own published content, without storing or publishing
+/// anything.
+pub fn suggest_price(
+    config: CliConfig,
+    format: OutputFormat,
+    file: &Path,
+    title: Option<String>,
+    tags: Vec<String>,
+) -> CliResult<String> {
+    if !file.is_file() {
+        return Err(CliError::FileNotFound(file.display().to_string()));
+    }
+
+    let size = std::fs::metadata(file)?.len();
+    let title = title.unwrap_or_else(|| default_title(file));
+    let metadata = Metadata::new(&title, size).with_tags(tags);
+
+    let ctx = NodeContext::local_read_only(config)?;
+    let suggestion = ctx.ops.suggest_price(ContentType::L0, &metadata)?;
+
+    let output = PriceSuggestionOutput {
+        title,
+        suggestion,
+        default_price: ctx.config.economics.default_price_units(),
+    };
+
+    Ok(output.render(format))
+}
+
+/// Publish a file using an existing node context.
+///
+/// The node is already part of the GossipSub mesh, so this does not wait
+/// for propagation.
+#[allow(clippy::too_many_arguments)]
+pub async fn publish_with_context(
+    ctx: &mut NodeContext,
+    format: OutputFormat,
+    file: &Path,
+    price: Option<f64>,
+    visibility: Visibility,
+    title: Option<String>,
+    description: Option<String>,
+    publish_at: Option<u64>,
+    schema: Option<String>,
+    fields: Option<String>,
+    tags: Vec<String>,
+) -> CliResult<String> {
+    if !file.is_file() {
+        return Err(CliError::FileNotFound(file.display().to_string()));
+    }
+
+    let prepared = match prepare_content(
+        &ctx.config,
+        format,
+        file,
+        price,
+        title,
+        schema,
+        fields,
+        tags,
+    )? {
+        Some(prepared) => prepared,
+        None => return Ok("Cancelled.".to_string()),
+    };
+
+    let spinner = progress::hidden();
+    match publish_prepared(
+        ctx,
+        format,
+        &prepared,
+        visibility,
+        description,
+        publish_at,
+        &spinner,
+    )
+    .await?
+    {
+        Some(output) => Ok(output.render(format)),
+        None => Ok("Cancelled.".to_string()),
+    }
+}
+
+/// File content validated and ready to publish.
+struct PreparedContent {
+    /// File the content was read from.
+    file: PathBuf,
+    /// Raw content.
+    content: Vec<u8>,
+    /// Content title.
+    title: String,
+    /// Price in units.
+    price_units: u64,
+    /// Schema URI for the structured fields.
+    schema: Option<String>,
+    /// Structured fields (JSON object).
+    fields: Option<String>,
+    /// Author's tags.
+    tags: Vec<String>,
+}
+
+/// Read and validate a file before publishing.
+///
+/// Returns `None` if the user cancelled.
+#[allow(clippy::too_many_arguments)]
+fn prepare_content(
+    config: &CliConfig,
+    format: OutputFormat,
+    file: &Path,
+    price: Option<f64>,
+    title: Option<String>,
+    schema: Option<String>,
+    fields: Option<String>,
+    tags: Vec<String>,
+) -> CliResult<Option<PreparedContent>> {
     // Read file content
     let content = std::fs::read(file)?;
 
@@ -61,19 +248,12 @@ pub async fn publish(
             "Warning".yellow().bold()
         );
         if crate::prompt::is_interactive() && !crate::prompt::confirm("Publish anyway?")? {
-            return Ok("Cancelled.".to_string());
+            return Ok(None);
         }
     }
 
-    spinner.set_message("Hashing content...");
-
     // Get title from filename if not provided
-    let title = title.unwrap_or_else(|| {
-        file.file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("Untitled")
-            .to_string()
-    });
+    let title = title.unwrap_or_else(|| default_title(file));
 
     // Convert price to units
     let price_units = price
@@ -96,23 +276,58 @@ pub async fn publish(
         })?;
     }
 
-    // Initialize context with network
-    spinner.set_message("Connecting to network...");
-    let mut ctx = NodeContext::with_network(config).await?;
+    Ok(Some(PreparedContent {
+        file: file.to_path_buf(),
+        content,
+        title,
+        price_units,
+        schema,
+        fields,
+        tags,
+    }))
+}
 
-    // Bootstrap the network to find peers
-    ctx.bootstrap().await?;
+/// Title for a file published without one: its file name.
+fn default_title(file: &Path) -> String {
+    file.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("Untitled")
+        .to_string()
+}
 
-    // Subscribe to announcements (required for GossipSub mesh formation)
-    if let Some(ref network) = ctx.network {
-        network.subscribe_announcements().await?;
-    }
+/// Store, extract, and publish prepared content.
+///
+/// Returns `None` if the user cancelled.
+async fn publish_prepared(
+    ctx: &mut NodeContext,
+    format: OutputFormat,
+    prepared: &PreparedContent,
+    visibility: Visibility,
+    description: Option<String>,
+    publish_at: Option<u64>,
+    spinner: &ProgressBar,
+) -> CliResult<Option<PublishOutput>> {
+    let PreparedContent {
+        file,
+        content,
+        title,
+        price_units,
+        schema,
+        fields,
+        tags,
+    } = prepared;
+    let price_units = *price_units;
+
+    spinner.set_message("Hashing content...");
 
     // Create metadata
-    let mut metadata = Metadata::new(&title, content.len() as u64);
+    let mut metadata = Metadata::new(title, content.len() as u64);
     if let Some(desc) = description {
         metadata = metadata.with_description(&desc);
     }
+    metadata.schema = schema.clone();
+    metadata.fields = fields.clone();
+    metadata.tags = tags.clone();
 
     // Detect mime type from extension
     if let Some(ext) = file.extension().and_then(|e| e.to_str()) {
@@ -130,7 +345,7 @@ pub async fn publish(
     }
 
     // Check if this content already exists (re-publish detection)
-    let computed_hash = content_hash(&content);
+    let computed_hash = content_hash(content);
     if let Ok(Some(existing)) = ctx.ops.get_content_manifest(&computed_hash) {
         if format == OutputFormat::Human {
             eprintln!(
@@ -146,7 +361,7 @@ pub async fn publish(
             );
             if crate::prompt::is_interactive() {
                 if !crate::prompt::confirm("Re-publish with new metadata?")? {
-                    return Ok("Cancelled.".to_string());
+                    return Ok(None);
                 }
             } else {
                 return Err(CliError::user(format!(
@@ -164,7 +379,7 @@ pub async fn publish(
     }
 
     // Create content
-    let hash = ctx.ops.create_content(&content, metadata.clone())?;
+    let hash = ctx.ops.create_content(content, metadata.clone())?;
 
     // Extract L1 mentions (if L0 content)
     spinner.set_message("Extracting mentions...");
@@ -173,34 +388,45 @@ pub async fn publish(
         Err(_) => None,
     };
 
-    // Publish content
-    spinner.set_message("Publishing to network...");
-    ctx.ops
-        .publish_content(&hash, visibility, price_units)
-        .await?;
+    // Publish content (or schedule it)
+    match publish_at {
+        Some(publish_at) => {
+            spinner.set_message("Scheduling publish...");
+            ctx.ops
+                .schedule_publish(&hash, visibility, price_units, publish_at)
+                .await?;
+        }
+        None => {
+            spinner.set_message("Publishing to network...");
+            ctx.ops
+                .publish_content(&hash, visibility, price_units)
+                .await?;
+        }
+    }
 
-    // Wait for GossipSub propagation (needs time for mesh to form)
-    spinner.set_message("Propagating to network...");
-    let wait_secs = ctx.config.network.gossipsub_propagation_wait;
-    tokio::time::sleep(std::time::Duration::from_secs(wait_secs)).await;
-    spinner.finish_and_clear();
+    // Tags as filed on publish
+    let tags = ctx
+        .ops
+        .get_content_manifest(&hash)?
+        .map(|manifest| manifest.metadata.tags)
+        .unwrap_or_default();
 
-    // Create output
-    let output = PublishOutput {
+    Ok(Some(PublishOutput {
         hash: hash.to_string(),
-        title,
+        title: title.clone(),
         size: content.len() as u64,
         price: price_units,
         visibility: format!("{:?}", visibility),
         mentions,
-    };
-
-    Ok(output.render(format))
+        publish_at: publish_at.filter(|t| *t > nodalync_ops::current_timestamp()),
+        tags,
+    }))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use nodalync_store::{ManifestFilter, ManifestStore};
     use tempfile::TempDir;
 
     fn setup_config(temp_dir: &TempDir) -> CliConfig {
@@ -231,6 +457,10 @@ mod tests {
             Visibility::Shared,
             None,
             None,
+            None,
+            None,
+            None,
+            vec![],
         )
         .await;
 
@@ -264,6 +494,10 @@ mod tests {
             Visibility::Shared,
             None,
             None,
+            None,
+            None,
+            None,
+            vec![],
         )
         .await;
 
@@ -314,6 +548,10 @@ mod tests {
             Visibility::Shared,
             None,
             None,
+            None,
+            None,
+            None,
+            vec![],
         )
         .await;
 
@@ -363,6 +601,10 @@ mod tests {
             Visibility::Shared,
             None,
             None,
+            None,
+            None,
+            None,
+            vec![],
         )
         .await;
         assert!(
@@ -380,6 +622,10 @@ mod tests {
             Visibility::Shared,
             None,
             None,
+            None,
+            None,
+            None,
+            vec![],
         )
         .await;
         assert!(result.is_err(), "Duplicate publish should fail");
@@ -433,6 +679,10 @@ mod tests {
             Visibility::Shared,
             None,
             None,
+            None,
+            None,
+            None,
+            vec![],
         )
         .await;
 
@@ -451,4 +701,70 @@ mod tests {
             "Empty file error code should NOT be ACCESS_DENIED"
         );
     }
+
+    #[test]
+    fn test_suggest_price() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        crate::commands::init::init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let file = temp_dir.path().join("protein-folding.md");
+        std::fs::write(&file, "# Protein folding methods").unwrap();
+
+        let human = suggest_price(
+            config.clone(),
+            OutputFormat::Human,
+            &file,
+            None,
+            vec!["biology".to_string()],
+        )
+        .unwrap();
+        assert!(human.contains("No priced content"));
+
+        {
+            let ctx = NodeContext::local(config.clone()).unwrap();
+            let hash = content_hash(b"survey");
+            let mut l1_summary = nodalync_types::L1Summary::empty(hash);
+            l1_summary.primary_topics = vec!["biology".to_string()];
+            ctx.ops
+                .state
+                .store_announcement(nodalync_wire::AnnouncePayload {
+                    hash,
+                    content_type: ContentType::L0,
+                    title: "Protein Folding Survey".to_string(),
+                    l1_summary,
+                    price: 250,
+                    addresses: vec![],
+                    publisher_peer_id: None,
+                    publisher_key: None,
+                    signature: None,
+                    delegation: None,
+                });
+        }
+
+        let json = suggest_price(
+            config,
+            OutputFormat::Json,
+            &file,
+            None,
+            vec!["biology".to_string()],
+        )
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["title"], "protein-folding.md");
+        assert_eq!(value["suggestion"]["median"], 250);
+        assert_eq!(value["suggestion"]["comparables"], 1);
+
+        // Nothing was published
+        let ctx = NodeContext::local_read_only(setup_config(&temp_dir)).unwrap();
+        assert!(ctx
+            .ops
+            .state
+            .manifests
+            .list(ManifestFilter::new())
+            .unwrap()
+            .is_empty());
+    }
 }
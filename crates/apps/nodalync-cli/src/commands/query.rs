@@ -2,21 +2,91 @@
 
 use std::path::PathBuf;
 
+use indicatif::ProgressBar;
+use nodalync_crypto::Hash;
+use nodalync_ops::with_referrer;
+use nodalync_types::CapabilityToken;
+use serde::{Deserialize, Serialize};
+
+use super::channel::parse_peer_id;
 use crate::config::CliConfig;
 use crate::context::{parse_hash, NodeContext};
 use crate::error::{CliError, CliResult};
-use crate::output::{OutputFormat, QueryOutput, Render};
+use crate::ipc::{absolute_path, try_daemon_request, IpcRequest};
+use crate::output::{MeteredSummary, OutputFormat, QueryOutput, Render, TrialSummary};
 use crate::progress;
 
+/// When to stop a metered read; it stops at whichever comes first, or
+/// at the end of the content.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MeteredRead {
+    /// Stop once the content read contains this text.
+    #[serde(default)]
+    pub until: Option<String>,
+    /// Stop once at least this many bytes are read.
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+}
+
+impl MeteredRead {
+    /// Whether `content`, read so far, is enough.
+    pub fn is_enough(&self, content: &[u8]) -> bool {
+        if self
+            .max_bytes
+            .is_some_and(|max_bytes| content.len() as u64 >= max_bytes)
+        {
+            return true;
+        }
+        match &self.until {
+            Some(text) if !text.is_empty() => content
+                .windows(text.len())
+                .any(|window| window == text.as_bytes()),
+            _ => false,
+        }
+    }
+}
+
 /// Execute the query command.
+///
+/// With `section`, only that section of the content is bought, at its own
+/// price. With `metered`, metered content is read and paid for unit by unit
+/// until the read has enough. With `trial`, a free trial read is taken
+/// instead of paying. With `referrer`, that peer is named as having
+/// referred the query, and is paid the content's referral cut.
+#[allow(clippy::too_many_arguments)]
 pub async fn query(
     config: CliConfig,
     format: OutputFormat,
     hash_str: &str,
     output_path: Option<PathBuf>,
+    capability: Option<String>,
+    section: Option<u32>,
+    metered: Option<MeteredRead>,
+    trial: bool,
+    referrer: Option<String>,
 ) -> CliResult<String> {
-    // Parse hash
+    // Parse hash, capability token and referrer
     let hash = parse_hash(hash_str)?;
+    if let Some(capability) = &capability {
+        parse_capability(capability, &hash)?;
+    }
+    if let Some(referrer) = &referrer {
+        parse_peer_id(referrer)?;
+    }
+
+    // Forward to the running node if there is one
+    let request = IpcRequest::Query {
+        hash: hash_str.to_string(),
+        output: output_path.as_deref().map(absolute_path).transpose()?,
+        capability: capability.clone(),
+        section,
+        metered: metered.clone(),
+        trial,
+        referrer: referrer.clone(),
+    };
+    if let Some(output) = try_daemon_request(&config.base_dir(), format, request).await? {
+        return Ok(output);
+    }
 
     // Create spinner for human output
     let spinner = if format == OutputFormat::Human {
@@ -26,37 +96,287 @@ pub async fn query(
     };
 
     // Initialize context with network
-    let mut ctx = NodeContext::with_network(config.clone()).await?;
+    let mut ctx = NodeContext::with_network(config).await?;
 
     // Bootstrap to find peers
     ctx.bootstrap().await?;
 
+    query_in_context(
+        &mut ctx,
+        format,
+        hash_str,
+        output_path,
+        capability,
+        section,
+        metered,
+        trial,
+        referrer,
+        &spinner,
+    )
+    .await
+}
+
+/// Query content using an existing node context.
+#[allow(clippy::too_many_arguments)]
+pub async fn query_with_context(
+    ctx: &mut NodeContext,
+    format: OutputFormat,
+    hash_str: &str,
+    output_path: Option<PathBuf>,
+    capability: Option<String>,
+    section: Option<u32>,
+    metered: Option<MeteredRead>,
+    trial: bool,
+    referrer: Option<String>,
+) -> CliResult<String> {
+    query_in_context(
+        ctx,
+        format,
+        hash_str,
+        output_path,
+        capability,
+        section,
+        metered,
+        trial,
+        referrer,
+        &progress::hidden(),
+    )
+    .await
+}
+
+/// Shared body of [`query`] and [`query_with_context`].
+#[allow(clippy::too_many_arguments)]
+async fn query_in_context(
+    ctx: &mut NodeContext,
+    format: OutputFormat,
+    hash_str: &str,
+    output_path: Option<PathBuf>,
+    capability: Option<String>,
+    section: Option<u32>,
+    metered: Option<MeteredRead>,
+    trial: bool,
+    referrer: Option<String>,
+    spinner: &ProgressBar,
+) -> CliResult<String> {
+    // The referrer is named in every query sent for this read
+    let read = read_content(
+        ctx,
+        format,
+        hash_str,
+        output_path,
+        capability,
+        section,
+        metered,
+        trial,
+        spinner,
+    );
+    match referrer {
+        Some(referrer) => with_referrer(parse_peer_id(&referrer)?, read).await,
+        None => read.await,
+    }
+}
+
+/// Read content by whichever query was asked for.
+#[allow(clippy::too_many_arguments)]
+async fn read_content(
+    ctx: &mut NodeContext,
+    format: OutputFormat,
+    hash_str: &str,
+    output_path: Option<PathBuf>,
+    capability: Option<String>,
+    section: Option<u32>,
+    metered: Option<MeteredRead>,
+    trial: bool,
+    spinner: &ProgressBar,
+) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+
+    // A trial read pays nothing, so needs no price
+    if trial {
+        spinner.set_message("Reading free trial...");
+        let response = ctx.ops.query_trial(&hash).await?;
+        let output = QueryOutput {
+            hash: response.manifest.hash.to_string(),
+            title: response.manifest.metadata.title.clone(),
+            section: None,
+            metered: None,
+            trial: Some(TrialSummary {
+                reads_left: response.reads_left,
+                complete: response.complete,
+            }),
+            price_paid: 0,
+            saved_to: String::new(),
+        };
+        return save_response(
+            ctx,
+            format,
+            hash_str,
+            output_path,
+            &response.content,
+            output,
+            spinner,
+        );
+    }
+
+    if let Some(capability) = capability {
+        let token = parse_capability(&capability, &hash)?;
+
+        // Shared content is usually unannounced, so the price is only
+        // known if we already hold its manifest
+        let price = ctx
+            .ops
+            .get_content_manifest(&hash)?
+            .map(|m| m.economics.price)
+            .unwrap_or(0);
+
+        spinner.set_message("Querying content...");
+        let response = ctx.ops.query_with_capability(&token, price).await?;
+        let title = response.manifest.metadata.title.clone();
+        let price_paid = response.receipt.amount;
+        let output = QueryOutput {
+            hash: response.manifest.hash.to_string(),
+            title,
+            section: None,
+            metered: None,
+            trial: None,
+            price_paid,
+            saved_to: String::new(),
+        };
+        return save_response(
+            ctx,
+            format,
+            hash_str,
+            output_path,
+            &response.content,
+            output,
+            spinner,
+        );
+    }
+
     spinner.set_message("Fetching content metadata...");
 
-    // Get manifest first to know price
-    let manifest = match ctx.ops.get_content_manifest(&hash)? {
-        Some(m) => m,
-        None => {
+    // Get manifest first to know price. Content with a dynamic price, like
+    // remote content, is previewed so the provider can quote it
+    let (title, price) = match ctx.ops.get_content_manifest(&hash)? {
+        Some(m) if m.economics.pricing.is_none() => (m.metadata.title, m.economics.price),
+        _ => {
             // Try preview for remote content (does DHT lookup)
-            ctx.ops
+            let preview = ctx
+                .ops
                 .preview_content(&hash)
                 .await
-                .map(|p| p.manifest)
-                .map_err(|_| CliError::NotFound(hash_str.to_string()))?
+                .map_err(|_| CliError::NotFound(hash_str.to_string()))?;
+            let price = ctx.ops.quote_price(&preview).await;
+            (preview.manifest.metadata.title, price)
         }
     };
 
-    let price = manifest.economics.price;
-    let title = manifest.metadata.title.clone();
+    // A section is bought at its own price, up to that of the whole content
+    if let Some(index) = section {
+        spinner.set_message("Querying section...");
+        let response = ctx.ops.query_section(&hash, index, price).await?;
+        let output = QueryOutput {
+            hash: response.manifest.hash.to_string(),
+            title,
+            section: Some(response.section.title),
+            metered: None,
+            trial: None,
+            price_paid: response.receipt.amount,
+            saved_to: String::new(),
+        };
+        return save_response(
+            ctx,
+            format,
+            hash_str,
+            output_path,
+            &response.content,
+            output,
+            spinner,
+        );
+    }
+
+    // A metered read pays per unit, up to the price of the whole content
+    if let Some(metered) = metered {
+        spinner.set_message("Reading metered content...");
+        let response = ctx
+            .ops
+            .query_metered(&hash, price, |content| metered.is_enough(content))
+            .await?;
+        let receipt = response.receipt;
+        let output = QueryOutput {
+            hash: response.manifest.hash.to_string(),
+            title,
+            section: None,
+            metered: Some(MeteredSummary {
+                units: receipt.units,
+                total_units: receipt.total_units,
+                full_price: receipt.full_price,
+            }),
+            trial: None,
+            price_paid: receipt.amount,
+            saved_to: String::new(),
+        };
+        return save_response(
+            ctx,
+            format,
+            hash_str,
+            output_path,
+            &response.content,
+            output,
+            spinner,
+        );
+    }
 
     // Query content
     spinner.set_message("Querying content...");
     let response = ctx.ops.query_content(&hash, price, None).await?;
+    let output = QueryOutput {
+        hash: response.manifest.hash.to_string(),
+        title,
+        section: None,
+        metered: None,
+        trial: None,
+        price_paid: price,
+        saved_to: String::new(),
+    };
+    save_response(
+        ctx,
+        format,
+        hash_str,
+        output_path,
+        &response.content,
+        output,
+        spinner,
+    )
+}
+
+/// Decode a capability token and check it is for `hash`.
+fn parse_capability(capability: &str, hash: &Hash) -> CliResult<CapabilityToken> {
+    let token = nodalync_wire::decode_capability(capability)
+        .map_err(|e| CliError::User(format!("Invalid capability token: {}", e)))?;
+    if token.content_hash != *hash {
+        return Err(CliError::User(
+            "Capability token was issued for different content".into(),
+        ));
+    }
+    Ok(token)
+}
+
+/// Save queried content to disk and render the query output.
+fn save_response(
+    ctx: &NodeContext,
+    format: OutputFormat,
+    hash_str: &str,
+    output_path: Option<PathBuf>,
+    content: &[u8],
+    mut output: QueryOutput,
+    spinner: &ProgressBar,
+) -> CliResult<String> {
     spinner.set_message("Saving content...");
 
     // Determine output path
     let save_path = output_path.unwrap_or_else(|| {
-        let cache_dir = config.storage.cache_dir.clone();
+        let cache_dir = ctx.config.storage.cache_dir.clone();
         std::fs::create_dir_all(&cache_dir).ok();
         cache_dir.join(hash_str)
     });
@@ -65,16 +385,10 @@ pub async fn query(
     if let Some(parent) = save_path.parent() {
         std::fs::create_dir_all(parent)?;
     }
-    std::fs::write(&save_path, &response.content)?;
+    std::fs::write(&save_path, content)?;
     spinner.finish_and_clear();
 
-    let output = QueryOutput {
-        hash: hash.to_string(),
-        title,
-        price_paid: price,
-        saved_to: save_path.display().to_string(),
-    };
-
+    output.saved_to = save_path.display().to_string();
     Ok(output.render(format))
 }
 
@@ -103,7 +417,53 @@ mod tests {
 
         init(config.clone(), OutputFormat::Human, false).unwrap();
 
-        let result = query(config, OutputFormat::Human, "invalidhash", None).await;
+        let result = query(
+            config.clone(),
+            OutputFormat::Human,
+            "invalidhash",
+            None,
+            None,
+            None,
+            None,
+            false,
+            None,
+        )
+        .await;
         assert!(result.is_err());
+
+        let hash = nodalync_crypto::content_hash(b"shared").to_string();
+        let result = query(
+            config,
+            OutputFormat::Human,
+            &hash,
+            None,
+            Some("not-a-token".to_string()),
+            None,
+            None,
+            false,
+            None,
+        )
+        .await;
+        assert!(matches!(result, Err(CliError::User(_))));
+    }
+
+    #[test]
+    fn test_metered_read_is_enough() {
+        let read = MeteredRead {
+            until: Some("## Results".to_string()),
+            max_bytes: None,
+        };
+        assert!(!read.is_enough(b"# Intro\nsome text"));
+        assert!(read.is_enough(b"# Intro\n## Results\n"));
+
+        let read = MeteredRead {
+            until: None,
+            max_bytes: Some(4),
+        };
+        assert!(!read.is_enough(b"abc"));
+        assert!(read.is_enough(b"abcd"));
+
+        // No cut-off reads to the end
+        assert!(!MeteredRead::default().is_enough(b"anything"));
     }
 }
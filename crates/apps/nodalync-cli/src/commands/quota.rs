@@ -0,0 +1,166 @@
+//! Daily quota command.
+
+use nodalync_crypto::peer_id_to_string;
+use nodalync_types::QuotaPolicy;
+
+use crate::commands::channel::parse_peer_id;
+use crate::config::CliConfig;
+use crate::context::{parse_hash, NodeContext};
+use crate::error::{CliError, CliResult};
+use crate::output::{OutputFormat, QuotaOutput, QuotaUsageInfo, Render};
+
+/// Execute the quota command.
+///
+/// Limits each peer to `queries` queries and `bytes` bytes a day when
+/// either is given, replacing any earlier quota; lifts the quota with
+/// `off`; forgets a peer's usage today with `reset`; and always shows the
+/// quota and today's usage.
+pub fn quota(
+    config: CliConfig,
+    format: OutputFormat,
+    hash_str: &str,
+    queries: Option<u32>,
+    bytes: Option<u64>,
+    off: bool,
+    reset: Option<&str>,
+) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+    let reset = reset.map(parse_peer_id).transpose()?;
+    let ctx = NodeContext::local(config)?;
+
+    let manifest = ctx
+        .ops
+        .get_content_manifest(&hash)?
+        .ok_or_else(|| CliError::NotFound(hash_str.to_string()))?;
+    if manifest.owner != ctx.peer_id() {
+        return Err(CliError::User("You don't own this content".to_string()));
+    }
+
+    if off {
+        ctx.ops.set_content_quota(&hash, None)?;
+    } else if queries.is_some() || bytes.is_some() {
+        let policy = QuotaPolicy { queries, bytes };
+        ctx.ops.set_content_quota(&hash, Some(policy))?;
+    }
+    if let Some(peer) = &reset {
+        ctx.ops.reset_quota(peer)?;
+    }
+
+    let manifest = ctx
+        .ops
+        .get_content_manifest(&hash)?
+        .ok_or_else(|| CliError::NotFound(hash_str.to_string()))?;
+
+    let output = QuotaOutput {
+        hash: hash.to_string(),
+        title: manifest.metadata.title,
+        quota: manifest.access.quota,
+        node_quota: ctx.ops.config.quota,
+        usage: ctx
+            .ops
+            .quota_usage(Some(&hash))?
+            .into_iter()
+            .map(|(peer, usage)| QuotaUsageInfo {
+                peer_id: peer_id_to_string(&peer),
+                queries: usage.queries,
+                bytes: usage.bytes,
+            })
+            .collect(),
+    };
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use crate::commands::publish::publish;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_types::Visibility;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_quota() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let text = "A dataset read often enough to need limits.";
+        let file = temp_dir.path().join("data.txt");
+        std::fs::write(&file, text).unwrap();
+        publish(
+            config.clone(),
+            OutputFormat::Json,
+            &file,
+            Some(1.0),
+            Visibility::Shared,
+            Some("Dataset".to_string()),
+            None,
+            None,
+            None,
+            None,
+            vec![],
+        )
+        .await
+        .unwrap();
+        let hash = content_hash(text.as_bytes()).to_string();
+
+        let result = quota(
+            config.clone(),
+            OutputFormat::Human,
+            &hash,
+            None,
+            None,
+            false,
+            None,
+        )
+        .unwrap();
+        assert!(result.contains("No quota"));
+
+        let result = quota(
+            config.clone(),
+            OutputFormat::Json,
+            &hash,
+            Some(100),
+            Some(1_048_576),
+            false,
+            None,
+        )
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(value["quota"]["queries"], 100);
+        assert_eq!(value["quota"]["bytes"], 1_048_576);
+        assert_eq!(value["usage"].as_array().unwrap().len(), 0);
+
+        // Resetting a peer without usage is harmless
+        let (_, public_key) = generate_identity();
+        let peer = peer_id_to_string(&peer_id_from_public_key(&public_key));
+        let result = quota(
+            config.clone(),
+            OutputFormat::Human,
+            &hash,
+            None,
+            None,
+            false,
+            Some(&peer),
+        )
+        .unwrap();
+        assert!(result.contains("100 queries"));
+
+        let result = quota(config, OutputFormat::Json, &hash, None, None, true, None).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(value.get("quota").is_none());
+    }
+}
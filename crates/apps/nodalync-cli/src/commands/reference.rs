@@ -14,7 +14,7 @@ pub fn reference(config: CliConfig, format: OutputFormat, l3_hash_str: &str) ->
     let l3_hash = parse_hash(l3_hash_str)?;
 
     // Initialize context
-    let mut ctx = NodeContext::local(config)?;
+    let ctx = NodeContext::local(config)?;
 
     // Create L0 reference from L3
     let l0_hash = ctx.ops.reference_l3_as_l0(&l3_hash)?;
@@ -0,0 +1,127 @@
+//! Referral cut command.
+
+use crate::config::CliConfig;
+use crate::context::{parse_hash, NodeContext};
+use crate::error::{CliError, CliResult};
+use crate::output::{OutputFormat, ReferralOutput, Render};
+
+/// Execute the referral command.
+///
+/// Offers `percent` of each payment to whoever refers a query when given,
+/// stops offering it with `off`, and otherwise shows the current cut.
+pub fn referral(
+    config: CliConfig,
+    format: OutputFormat,
+    hash_str: &str,
+    percent: Option<f64>,
+    off: bool,
+) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+    let ctx = NodeContext::local(config)?;
+
+    let manifest = ctx
+        .ops
+        .get_content_manifest(&hash)?
+        .ok_or_else(|| CliError::NotFound(hash_str.to_string()))?;
+    if manifest.owner != ctx.peer_id() {
+        return Err(CliError::User("You don't own this content".to_string()));
+    }
+
+    if off {
+        ctx.ops.set_content_referral(&hash, None)?;
+    } else if let Some(percent) = percent {
+        let basis_points = (percent * 100.0).round();
+        if !(1.0..=u32::MAX as f64).contains(&basis_points) {
+            return Err(CliError::User(format!(
+                "Invalid referral cut: {}%",
+                percent
+            )));
+        }
+        ctx.ops
+            .set_content_referral(&hash, Some(basis_points as u32))?;
+    }
+
+    let manifest = ctx
+        .ops
+        .get_content_manifest(&hash)?
+        .ok_or_else(|| CliError::NotFound(hash_str.to_string()))?;
+
+    let output = ReferralOutput {
+        hash: hash.to_string(),
+        title: manifest.metadata.title,
+        price: manifest.economics.price,
+        referral: manifest.economics.referral,
+    };
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use crate::commands::publish::publish;
+    use nodalync_crypto::content_hash;
+    use nodalync_types::Visibility;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_referral() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let text = "A report search nodes can send buyers to.";
+        let file = temp_dir.path().join("report.txt");
+        std::fs::write(&file, text).unwrap();
+        publish(
+            config.clone(),
+            OutputFormat::Json,
+            &file,
+            Some(1.0),
+            Visibility::Shared,
+            Some("Report".to_string()),
+            None,
+            None,
+            None,
+            None,
+            vec![],
+        )
+        .await
+        .unwrap();
+        let hash = content_hash(text.as_bytes()).to_string();
+
+        let result = referral(config.clone(), OutputFormat::Human, &hash, None, false).unwrap();
+        assert!(result.contains("No referral cut"));
+
+        let result = referral(config.clone(), OutputFormat::Json, &hash, Some(2.5), false).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(value["referral"]["basis_points"], 250);
+
+        // Cuts above the maximum, or below a basis point, are refused
+        assert!(referral(config.clone(), OutputFormat::Json, &hash, Some(50.0), false).is_err());
+        assert!(referral(
+            config.clone(),
+            OutputFormat::Json,
+            &hash,
+            Some(0.001),
+            false
+        )
+        .is_err());
+
+        let result = referral(config, OutputFormat::Json, &hash, None, true).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(value.get("referral").is_none());
+    }
+}
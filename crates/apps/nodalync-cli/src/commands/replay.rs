@@ -0,0 +1,150 @@
+//! Replay command.
+//!
+//! Feeds a replay log (written by a node with `[network] replay_log = true`)
+//! back through the message handlers against fresh in-memory state, to
+//! reproduce how a node reacted to what it received. Nothing is sent.
+
+use std::path::Path;
+
+use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+use nodalync_net::{read_replay_log, ReplayDirection, ReplayKind};
+use nodalync_ops::{DefaultNodeOperations, ReplayOutcome};
+use nodalync_store::NodeState;
+
+use crate::error::{CliError, CliResult};
+use crate::output::{OutputFormat, Render, ReplayEntryOutput, ReplayOutput};
+
+/// Execute the replay command.
+///
+/// Inbound requests and broadcasts are replayed in order, each at the time
+/// it was recorded; everything else is listed alongside.
+pub async fn replay(format: OutputFormat, log: &Path) -> CliResult<String> {
+    if !log.exists() {
+        return Err(CliError::FileNotFound(log.display().to_string()));
+    }
+    let entries = read_replay_log(log)?;
+
+    let (_, public_key) = generate_identity();
+    let ops = DefaultNodeOperations::with_defaults(
+        NodeState::open_in_memory()?,
+        peer_id_from_public_key(&public_key),
+    );
+
+    let mut output = ReplayOutput {
+        log: log.display().to_string(),
+        entries: Vec::with_capacity(entries.len()),
+        responded: 0,
+        replayed: 0,
+        undecodable: 0,
+        failed: 0,
+    };
+    for entry in &entries {
+        let outcome = ops.replay_entry(entry).await;
+        let (name, response, error) = match outcome {
+            ReplayOutcome::Responded(message_type) => {
+                output.responded += 1;
+                ("responded", Some(format!("{:?}", message_type)), None)
+            }
+            ReplayOutcome::NoResponse => ("no_response", None, None),
+            ReplayOutcome::Handled => ("handled", None, None),
+            ReplayOutcome::Skipped => ("skipped", None, None),
+            ReplayOutcome::Failed(e) => {
+                output.failed += 1;
+                ("failed", None, Some(e))
+            }
+        };
+        if name != "skipped" {
+            output.replayed += 1;
+        }
+        if entry.decode_error.is_some() {
+            output.undecodable += 1;
+        }
+
+        output.entries.push(ReplayEntryOutput {
+            seq: entry.seq,
+            timestamp: entry.timestamp,
+            direction: match entry.direction {
+                ReplayDirection::Inbound => "inbound",
+                ReplayDirection::Outbound => "outbound",
+            }
+            .to_string(),
+            kind: match entry.kind {
+                ReplayKind::Request => "request",
+                ReplayKind::Response => "response",
+                ReplayKind::Broadcast => "broadcast",
+            }
+            .to_string(),
+            message_type: entry.message_type.map(|t| format!("{:?}", t)),
+            remote: entry.peer.clone().or_else(|| entry.topic.clone()),
+            decode_error: entry.decode_error.clone(),
+            outcome: name.to_string(),
+            response,
+            error,
+        });
+    }
+
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_net::{ReplayEntry, ReplayLog, ReplayLogConfig};
+    use nodalync_ops::current_timestamp;
+    use nodalync_wire::{create_message, encode_message, encode_payload, MessageType, PingPayload};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_replay() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("replay.log");
+        let (private_key, public_key) = generate_identity();
+        let now = current_timestamp();
+        let ping = encode_message(&create_message(
+            MessageType::Ping,
+            encode_payload(&PingPayload { nonce: 1 }).unwrap(),
+            peer_id_from_public_key(&public_key),
+            now,
+            &private_key,
+        ))
+        .unwrap();
+
+        let mut log = ReplayLog::open(&ReplayLogConfig::new(&path)).unwrap();
+        let peer = nodalync_net::PeerId::random();
+        for entry in [
+            ReplayEntry::new(ReplayDirection::Inbound, ReplayKind::Request, &ping, now)
+                .with_peer(peer),
+            ReplayEntry::new(
+                ReplayDirection::Outbound,
+                ReplayKind::Response,
+                b"pong",
+                now,
+            ),
+            ReplayEntry::new(ReplayDirection::Inbound, ReplayKind::Request, b"junk", now)
+                .with_peer(peer),
+        ] {
+            log.record(entry).unwrap();
+        }
+        drop(log);
+
+        let human = replay(OutputFormat::Human, &path).await.unwrap();
+        assert!(human.contains("-> Pong"));
+        assert!(human.contains("3 entries, 2 replayed, 1 answered, 2 undecodable, 0 failed"));
+
+        let json = replay(OutputFormat::Json, &path).await.unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let entries = value["entries"].as_array().unwrap();
+        assert_eq!(entries[0]["message_type"], "Ping");
+        assert_eq!(entries[0]["remote"], peer.to_string());
+        assert_eq!(entries[0]["response"], "Pong");
+        assert_eq!(entries[1]["outcome"], "skipped");
+        assert_eq!(entries[2]["outcome"], "no_response");
+        assert!(entries[2]["decode_error"].is_string());
+
+        assert!(matches!(
+            replay(OutputFormat::Human, &temp_dir.path().join("missing.log")).await,
+            Err(CliError::FileNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,171 @@
+//! Replica commands.
+
+use std::path::Path;
+
+use nodalync_wire::{decode_payload, encode_payload, ReplicaCatalog};
+
+use super::channel::parse_peer_id;
+use crate::config::CliConfig;
+use crate::context::NodeContext;
+use crate::error::{CliError, CliResult};
+use crate::output::{
+    OutputFormat, Render, ReplicaDelegationInfo, ReplicaExportOutput, ReplicaImportOutput,
+    ReplicaListOutput,
+};
+use crate::prompt::get_identity_password;
+
+/// Milliseconds in a day.
+const DAY_MS: u64 = 86_400_000;
+
+/// Export our published catalog for a replica to serve.
+pub fn export_replica(
+    config: CliConfig,
+    format: OutputFormat,
+    replica_str: &str,
+    file: &Path,
+    days: u64,
+) -> CliResult<String> {
+    let replica = parse_peer_id(replica_str)?;
+    if days == 0 {
+        return Err(CliError::User("--days must be at least 1".to_string()));
+    }
+
+    let mut ctx = NodeContext::local(config)?;
+    let password = get_identity_password()?;
+    let (private_key, _) = ctx.ops.state.identity.load(&password).map_err(|e| {
+        if matches!(e, nodalync_store::StoreError::Encryption(_)) {
+            CliError::User(e.to_string())
+        } else {
+            CliError::from(e)
+        }
+    })?;
+    ctx.ops.set_private_key(private_key);
+
+    let expires_at = nodalync_ops::current_timestamp().saturating_add(days.saturating_mul(DAY_MS));
+    let catalog = ctx.ops.export_replica_catalog(&replica, expires_at)?;
+    let bytes = encode_payload(&catalog)
+        .map_err(|e| CliError::User(format!("Failed to encode replica catalog: {}", e)))?;
+    std::fs::write(file, &bytes)?;
+
+    Ok(ReplicaExportOutput {
+        replica: replica.to_string(),
+        file: file.display().to_string(),
+        items: catalog.items.len(),
+        expires_at: catalog.delegation.expires_at,
+        settlement_account: catalog.delegation.settlement_account,
+    }
+    .render(format))
+}
+
+/// Import a primary's catalog to serve it.
+///
+/// A running node announces the content on its next start.
+pub fn import_replica(config: CliConfig, format: OutputFormat, file: &Path) -> CliResult<String> {
+    let bytes = std::fs::read(file)?;
+    let catalog: ReplicaCatalog = decode_payload(&bytes)
+        .map_err(|e| CliError::User(format!("Not a replica catalog: {}", e)))?;
+
+    let ctx = NodeContext::local(config)?;
+    let import = ctx.ops.import_replica_catalog(&catalog)?;
+
+    Ok(ReplicaImportOutput {
+        primary: import.primary.to_string(),
+        file: file.display().to_string(),
+        expires_at: import.expires_at,
+        manifests_added: import.manifests_added,
+        manifests_updated: import.manifests_updated,
+        manifests_withdrawn: import.manifests_withdrawn,
+        account_registered: import.account_registered,
+    }
+    .render(format))
+}
+
+/// List the primaries whose catalogs we serve.
+pub fn list_replicas(config: CliConfig, format: OutputFormat) -> CliResult<String> {
+    let ctx = NodeContext::local_read_only(config)?;
+    let now = nodalync_ops::current_timestamp();
+    let delegations = ctx
+        .ops
+        .list_replica_delegations()?
+        .into_iter()
+        .map(|d| ReplicaDelegationInfo {
+            primary: d.primary.to_string(),
+            issued_at: d.issued_at,
+            expires_at: d.expires_at,
+            expired: d.is_expired(now),
+            settlement_account: d.settlement_account,
+        })
+        .collect();
+
+    Ok(ReplicaListOutput { delegations }.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use crate::commands::publish::publish;
+    use nodalync_crypto::peer_id_to_string;
+    use nodalync_types::Visibility;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_export_and_import_catalog() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let primary_dir = TempDir::new().unwrap();
+        let primary = setup_config(&primary_dir);
+        init(primary.clone(), OutputFormat::Human, false).unwrap();
+        let file_path = primary_dir.path().join("notes.txt");
+        std::fs::write(&file_path, "Notes served by a replica").unwrap();
+        publish(
+            primary.clone(),
+            OutputFormat::Human,
+            &file_path,
+            Some(0.0),
+            Visibility::Shared,
+            Some("Notes".to_string()),
+            None,
+            None,
+            None,
+            None,
+            vec![],
+        )
+        .await
+        .unwrap();
+
+        let replica_dir = TempDir::new().unwrap();
+        let replica = setup_config(&replica_dir);
+        init(replica.clone(), OutputFormat::Human, false).unwrap();
+        let replica_peer = NodeContext::local(replica.clone()).unwrap().peer_id();
+
+        let catalog = primary_dir.path().join("catalog.replica");
+        let output = export_replica(
+            primary,
+            OutputFormat::Json,
+            &peer_id_to_string(&replica_peer),
+            &catalog,
+            30,
+        )
+        .unwrap();
+        let exported: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(exported["items"], 1);
+
+        let output = import_replica(replica.clone(), OutputFormat::Json, &catalog).unwrap();
+        let imported: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(imported["manifests_added"], 1);
+
+        let output = list_replicas(replica, OutputFormat::Human).unwrap();
+        assert!(output.contains("Serving 1 primary"));
+    }
+}
@@ -0,0 +1,73 @@
+//! Data retention status command.
+
+use crate::config::CliConfig;
+use crate::context::NodeContext;
+use crate::error::CliResult;
+use crate::output::{OutputFormat, Render, RetentionCategoryStatus, RetentionStatusOutput};
+
+/// Execute the retention status command.
+///
+/// Reports what each retention category holds and how much of it is past
+/// the configured limit.
+pub fn retention_status(config: CliConfig, format: OutputFormat) -> CliResult<String> {
+    let retention = config.retention.ops_config();
+    let mut ctx = NodeContext::local_read_only(config)?;
+    // Local contexts run with default ops settings; report against ours
+    ctx.ops.config.retention = retention;
+
+    let output = RetentionStatusOutput {
+        categories: ctx
+            .ops
+            .retention_status()?
+            .into_iter()
+            .map(|s| RetentionCategoryStatus {
+                category: s.category.to_string(),
+                ttl_days: s.ttl_secs.map(|secs| secs / (24 * 60 * 60)),
+                records: s.stats.records,
+                expired: s.stats.expired,
+                oldest: s.stats.oldest,
+            })
+            .collect(),
+    };
+
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config
+    }
+
+    #[test]
+    fn test_retention_status() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = setup_config(&temp_dir);
+        config.retention.analytics_days = Some(30);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let human = retention_status(config.clone(), OutputFormat::Human).unwrap();
+        assert!(human.contains("Data Retention"));
+        assert!(human.contains("keep 30 days"));
+        assert!(human.contains("keep forever"));
+
+        let json = retention_status(config, OutputFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let categories = value["categories"].as_array().unwrap();
+        assert_eq!(categories.len(), 5);
+        assert_eq!(categories[4]["category"], "analytics");
+        assert_eq!(categories[4]["ttl_days"], 30);
+        assert_eq!(categories[0]["ttl_days"], serde_json::Value::Null);
+    }
+}
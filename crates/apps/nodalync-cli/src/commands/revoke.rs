@@ -0,0 +1,37 @@
+//! Key revocation command.
+
+use std::path::Path;
+
+use nodalync_types::RevocationCertificate;
+
+use crate::config::CliConfig;
+use crate::context::NodeContext;
+use crate::error::{CliError, CliResult};
+use crate::output::{OutputFormat, Render, RevokeOutput};
+
+/// Broadcast a revocation certificate, retiring the key it was made for.
+pub async fn revoke(config: CliConfig, format: OutputFormat, file: &Path) -> CliResult<String> {
+    if !file.exists() {
+        return Err(CliError::FileNotFound(file.display().to_string()));
+    }
+    let certificate: RevocationCertificate = serde_json::from_str(&std::fs::read_to_string(file)?)
+        .map_err(|e| CliError::User(format!("Not a revocation certificate: {}", e)))?;
+
+    if !config.network.enabled {
+        return Err(CliError::User(
+            "Revocations are broadcast to the network; enable networking in the config".into(),
+        ));
+    }
+
+    let ctx = NodeContext::with_network(config).await?;
+    ctx.bootstrap().await?;
+
+    let newly_revoked = ctx.ops.revoke_key(&certificate).await?;
+
+    let output = RevokeOutput {
+        peer_id: certificate.peer_id.to_string(),
+        own_key: certificate.peer_id == ctx.peer_id(),
+        newly_revoked,
+    };
+    Ok(output.render(format))
+}
@@ -1,9 +1,12 @@
 //! Search command.
 
+use nodalync_ops::SearchSource;
 use nodalync_store::{ManifestFilter, ManifestStore};
 use nodalync_types::ContentType;
+use nodalync_wire::SearchFilters;
 
-use crate::config::CliConfig;
+use super::channel::parse_peer_id;
+use crate::config::{hbar_to_tinybars, CliConfig};
 use crate::context::NodeContext;
 use crate::error::CliResult;
 use crate::output::{OutputFormat, Render, SearchOutput, SearchResult};
@@ -13,39 +16,61 @@ pub async fn search(
     config: CliConfig,
     format: OutputFormat,
     query: &str,
-    content_type: Option<ContentType>,
+    filters: SearchFilters,
     limit: u32,
     all: bool,
 ) -> CliResult<String> {
     if all {
         // Network search: local + cached announcements + peer queries
-        search_network(config, format, query, content_type, limit).await
+        search_network(config, format, query, &filters, limit).await
     } else {
         // Local-only search
-        search_local(config, format, query, content_type, limit)
+        search_local(config, format, query, &filters, limit)
     }
 }
 
+/// Build search filters from the command-line flags.
+///
+/// Prices are in HBAR; `published_after` is Unix milliseconds.
+pub fn search_filters(
+    content_type: Option<ContentType>,
+    min_price: Option<f64>,
+    max_price: Option<f64>,
+    tags: Vec<String>,
+    owner: Option<&str>,
+    published_after: Option<u64>,
+) -> CliResult<SearchFilters> {
+    Ok(SearchFilters {
+        content_types: content_type.map(|ct| vec![ct]),
+        min_price: min_price.map(hbar_to_tinybars),
+        max_price: max_price.map(hbar_to_tinybars),
+        tags: (!tags.is_empty()).then_some(tags),
+        owner: owner.map(parse_peer_id).transpose()?,
+        created_after: published_after,
+        ..Default::default()
+    })
+}
+
 /// Search local manifests only.
 fn search_local(
     config: CliConfig,
     format: OutputFormat,
     query: &str,
-    content_type: Option<ContentType>,
+    filters: &SearchFilters,
     limit: u32,
 ) -> CliResult<String> {
     // Initialize context (local only, no network needed)
     let state = NodeContext::for_init(config)?;
 
     // Build filter with text query
-    let mut filter = ManifestFilter::new().with_text_query(query).limit(limit);
-
-    if let Some(ct) = content_type {
-        filter = filter.with_content_type(ct);
-    }
+    let filter = ManifestFilter::new()
+        .with_search_filters(filters)
+        .with_text_query(query)
+        .limit(limit);
 
     // Search local manifests
     let manifests = state.manifests.list(filter)?;
+    let own_peer_id = state.identity.peer_id().ok();
 
     // Convert to search results
     let results: Vec<SearchResult> = manifests
@@ -58,6 +83,9 @@ fn search_local(
             owner: m.owner.to_string(),
             description: m.metadata.description.clone(),
             source: Some("local".to_string()),
+            peer: None,
+            latency_ms: None,
+            verified_publisher: Some(m.owner) == own_peer_id,
         })
         .collect();
 
@@ -78,7 +106,7 @@ async fn search_network(
     config: CliConfig,
     format: OutputFormat,
     query: &str,
-    content_type: Option<ContentType>,
+    filters: &SearchFilters,
     limit: u32,
 ) -> CliResult<String> {
     use crate::progress::{hidden, spinner};
@@ -91,13 +119,16 @@ async fn search_network(
     };
 
     // Initialize context with networking enabled
-    let mut ctx = NodeContext::with_network(config).await?;
+    let ctx = NodeContext::with_network(config).await?;
 
     pb.set_message("Bootstrapping...");
     ctx.bootstrap().await?;
 
     pb.set_message("Searching network...");
-    let results = ctx.ops.search_network(query, content_type, limit).await?;
+    let results = ctx
+        .ops
+        .search_network_filtered(query, filters, limit)
+        .await?;
 
     pb.finish_and_clear();
 
@@ -112,6 +143,12 @@ async fn search_network(
             owner: r.owner.to_string(),
             description: None,
             source: Some(r.source.to_string()),
+            peer: match r.source {
+                SearchSource::Peer => r.publisher_peer_id.clone(),
+                _ => None,
+            },
+            latency_ms: r.latency_ms,
+            verified_publisher: r.verified_publisher,
         })
         .collect();
 
@@ -146,13 +183,45 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let config = test_config(&temp_dir);
 
-        let result = search(config, OutputFormat::Human, "nonexistent", None, 20, false).await;
+        let result = search(
+            config,
+            OutputFormat::Human,
+            "nonexistent",
+            SearchFilters::default(),
+            20,
+            false,
+        )
+        .await;
         assert!(result.is_ok());
 
         let output = result.unwrap();
         assert!(output.contains("No results found"));
     }
 
+    #[test]
+    fn test_search_filters_from_flags() {
+        let filters = search_filters(
+            Some(ContentType::L3),
+            Some(0.5),
+            None,
+            vec!["rust".to_string()],
+            Some("0102030405060708090a0b0c0d0e0f1011121314"),
+            Some(1_000),
+        )
+        .unwrap();
+        assert_eq!(filters.content_types, Some(vec![ContentType::L3]));
+        assert_eq!(filters.min_price, Some(50_000_000));
+        assert_eq!(filters.max_price, None);
+        assert_eq!(filters.tags, Some(vec!["rust".to_string()]));
+        assert_eq!(filters.owner.unwrap().0[0], 1);
+        assert_eq!(filters.created_after, Some(1_000));
+
+        let none = search_filters(None, None, None, Vec::new(), None, None).unwrap();
+        assert!(none.is_empty());
+
+        assert!(search_filters(None, None, None, Vec::new(), Some("bogus"), None).is_err());
+    }
+
     #[test]
     fn test_search_output_json() {
         let output = SearchOutput {
@@ -165,6 +234,9 @@ mod tests {
                 owner: "peer123".to_string(),
                 description: Some("A test description".to_string()),
                 source: Some("local".to_string()),
+                peer: None,
+                latency_ms: None,
+                verified_publisher: true,
             }],
             total: 1,
             sources: Some("local".to_string()),
@@ -174,5 +246,33 @@ mod tests {
         assert!(json.contains("\"query\": \"test\""));
         assert!(json.contains("\"title\": \"Test Title\""));
         assert!(json.contains("\"source\": \"local\""));
+        assert!(!json.contains("latency_ms"));
+    }
+
+    #[test]
+    fn test_search_output_peer_latency() {
+        let output = SearchOutput {
+            query: "test".to_string(),
+            results: vec![SearchResult {
+                hash: "abc123".to_string(),
+                title: "Test Title".to_string(),
+                content_type: "L0".to_string(),
+                price: 1000,
+                owner: "peer123".to_string(),
+                description: None,
+                source: Some("peer".to_string()),
+                peer: Some("12D3KooWPeer".to_string()),
+                latency_ms: Some(42),
+                verified_publisher: true,
+            }],
+            total: 1,
+            sources: Some("local + network".to_string()),
+        };
+
+        assert!(output.render_human().contains("[peer, 42ms, verified]"));
+        let json = output.render(OutputFormat::Json);
+        assert!(json.contains("\"peer\": \"12D3KooWPeer\""));
+        assert!(json.contains("\"latency_ms\": 42"));
+        assert!(json.contains("\"verified_publisher\": true"));
     }
 }
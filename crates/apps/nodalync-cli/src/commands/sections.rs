@@ -0,0 +1,119 @@
+//! Priced sections command.
+
+use crate::config::{hbar_to_tinybars, CliConfig};
+use crate::context::{parse_hash, NodeContext};
+use crate::error::{CliError, CliResult};
+use crate::output::{OutputFormat, Render, SectionsOutput};
+
+/// Execute the sections command.
+///
+/// Replaces the sections when any prices are given, removes them with
+/// `clear`, and otherwise lists the current ones.
+pub fn sections(
+    config: CliConfig,
+    format: OutputFormat,
+    hash_str: &str,
+    prices: &[(String, f64)],
+    clear: bool,
+) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+    let ctx = NodeContext::local(config)?;
+
+    let manifest = ctx
+        .ops
+        .get_content_manifest(&hash)?
+        .ok_or_else(|| CliError::NotFound(hash_str.to_string()))?;
+    if manifest.owner != ctx.peer_id() {
+        return Err(CliError::User("You don't own this content".to_string()));
+    }
+
+    if clear {
+        ctx.ops.set_content_sections(&hash, Vec::new())?;
+    } else if !prices.is_empty() {
+        let prices: Vec<(String, u64)> = prices
+            .iter()
+            .map(|(heading, price)| (heading.clone(), hbar_to_tinybars(*price)))
+            .collect();
+        ctx.ops.set_section_prices(&hash, &prices)?;
+    }
+
+    let manifest = ctx
+        .ops
+        .get_content_manifest(&hash)?
+        .ok_or_else(|| CliError::NotFound(hash_str.to_string()))?;
+
+    let output = SectionsOutput {
+        hash: hash.to_string(),
+        title: manifest.metadata.title,
+        price: manifest.economics.price,
+        sections: manifest.metadata.sections,
+    };
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use crate::commands::publish::publish;
+    use nodalync_crypto::content_hash;
+    use nodalync_types::Visibility;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_sections() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let text = "# Guide\n\nIntro.\n\n## Setup\n\nSteps.\n\n## Tuning\n\nKnobs.\n";
+        let file = temp_dir.path().join("guide.md");
+        std::fs::write(&file, text).unwrap();
+        publish(
+            config.clone(),
+            OutputFormat::Json,
+            &file,
+            Some(1.0),
+            Visibility::Private,
+            Some("Guide".to_string()),
+            None,
+            None,
+            None,
+            None,
+            vec![],
+        )
+        .await
+        .unwrap();
+        let hash = content_hash(text.as_bytes()).to_string();
+
+        let result = sections(config.clone(), OutputFormat::Human, &hash, &[], false).unwrap();
+        assert!(result.contains("Sold whole only"));
+
+        let prices = [("Tuning".to_string(), 0.25), ("setup".to_string(), 0.0)];
+        let result = sections(config.clone(), OutputFormat::Json, &hash, &prices, false).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(value["sections"][0]["title"], "Setup");
+        assert_eq!(value["sections"][1]["title"], "Tuning");
+        assert_eq!(value["sections"][1]["price"], 25_000_000);
+
+        // Unknown headings are refused
+        let prices = [("Appendix".to_string(), 0.1)];
+        assert!(sections(config.clone(), OutputFormat::Json, &hash, &prices, false).is_err());
+
+        let result = sections(config, OutputFormat::Json, &hash, &[], true).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(value["sections"].as_array().unwrap().len(), 0);
+    }
+}
@@ -7,13 +7,25 @@ use nodalync_store::SettlementQueueStore;
 use crate::config::CliConfig;
 use crate::context::NodeContext;
 use crate::error::CliResult;
+use crate::ipc::{try_daemon_request, IpcRequest};
 use crate::output::{OutputFormat, Render, SettleOutput};
 
 /// Execute the settle command.
 pub async fn settle(config: CliConfig, format: OutputFormat) -> CliResult<String> {
+    // Forward to the running node if there is one
+    if let Some(output) = try_daemon_request(&config.base_dir(), format, IpcRequest::Settle).await?
+    {
+        return Ok(output);
+    }
+
     // Initialize context with network
     let mut ctx = NodeContext::with_network(config).await?;
 
+    settle_with_context(&mut ctx, format).await
+}
+
+/// Force settlement using an existing node context.
+pub async fn settle_with_context(ctx: &mut NodeContext, format: OutputFormat) -> CliResult<String> {
     // Get pending info before settlement
     let pending = ctx.ops.state.settlement.get_pending()?;
     let pending_total = ctx.ops.state.settlement.get_pending_total()?;
@@ -0,0 +1,120 @@
+//! Share content by capability token.
+
+use super::channel::parse_peer_id;
+use crate::config::CliConfig;
+use crate::context::{parse_hash, NodeContext};
+use crate::error::{CliError, CliResult};
+use crate::output::{OutputFormat, Render, ShareOutput};
+use crate::prompt::get_identity_password;
+
+/// Mint a capability token granting query access to local content.
+pub fn share(
+    config: CliConfig,
+    format: OutputFormat,
+    hash_str: &str,
+    peer: Option<&str>,
+    expires_in_hours: Option<u64>,
+) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+    let grantee = peer.map(parse_peer_id).transpose()?;
+
+    let mut ctx = NodeContext::local(config)?;
+    let manifest = ctx
+        .ops
+        .get_content_manifest(&hash)?
+        .ok_or_else(|| CliError::NotFound(hash_str.to_string()))?;
+
+    // Tokens are signed by the content owner
+    let password = get_identity_password()?;
+    let (private_key, _) = ctx.ops.state.identity.load(&password).map_err(|e| {
+        if matches!(e, nodalync_store::StoreError::Encryption(_)) {
+            CliError::User(e.to_string())
+        } else {
+            CliError::from(e)
+        }
+    })?;
+    ctx.ops.set_private_key(private_key);
+
+    let token = ctx.ops.mint_capability(
+        &hash,
+        grantee,
+        expires_in_hours.map(|hours| hours.saturating_mul(3_600_000)),
+    )?;
+    let encoded = nodalync_wire::encode_capability(&token)
+        .map_err(|e| CliError::User(format!("Failed to encode capability: {}", e)))?;
+
+    let output = ShareOutput {
+        hash: hash.to_string(),
+        title: manifest.metadata.title,
+        grantee: grantee.map(|peer| peer.to_string()),
+        expires_at: token.expires_at,
+        capability: encoded,
+    };
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use crate::commands::publish::publish;
+    use nodalync_types::Visibility;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_share_private_content() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        assert!(share(
+            config.clone(),
+            OutputFormat::Human,
+            "invalidhash",
+            None,
+            None
+        )
+        .is_err());
+
+        let file = temp_dir.path().join("draft.txt");
+        std::fs::write(&file, "A private draft to share.").unwrap();
+        let output = publish(
+            config.clone(),
+            OutputFormat::Json,
+            &file,
+            Some(0.0),
+            Visibility::Private,
+            Some("Draft".to_string()),
+            None,
+            None,
+            None,
+            None,
+            vec![],
+        )
+        .await
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let hash = json["hash"].as_str().unwrap().to_string();
+
+        let output = share(config, OutputFormat::Json, &hash, None, Some(2)).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["title"], "Draft");
+        assert!(json["grantee"].is_null());
+
+        let token = nodalync_wire::decode_capability(json["capability"].as_str().unwrap()).unwrap();
+        assert_eq!(token.content_hash.to_string(), hash);
+        assert_eq!(token.expires_at - token.issued_at, 2 * 3_600_000);
+    }
+}
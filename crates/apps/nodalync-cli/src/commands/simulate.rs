@@ -0,0 +1,125 @@
+//! Economics simulation command.
+//!
+//! Replays randomized payments through the revenue distribution and
+//! settlement rules and reports how the revenue was split, so the economics
+//! of the spec can be reviewed without running a node.
+
+use nodalync_econ::{simulate as run_simulation, SimulationConfig};
+
+use crate::config::hbar_to_tinybars;
+use crate::error::{CliError, CliResult};
+use crate::output::{OutputFormat, Render, SimulationOutput, SimulationRecipient};
+
+/// Simulation arguments passed from CLI.
+pub struct SimulateArgs {
+    pub seed: u64,
+    pub payments: usize,
+    pub contributors: usize,
+    pub sources: usize,
+    pub derived: usize,
+    pub max_inputs: usize,
+    pub max_price: f64,
+    pub batch_size: usize,
+    pub top: usize,
+}
+
+/// Execute the simulate command.
+pub fn simulate(format: OutputFormat, args: SimulateArgs) -> CliResult<String> {
+    let config = SimulationConfig {
+        seed: args.seed,
+        contributors: args.contributors,
+        sources: args.sources,
+        derived: args.derived,
+        max_inputs: args.max_inputs,
+        payments: args.payments,
+        min_amount: 1,
+        max_amount: hbar_to_tinybars(args.max_price),
+        batch_size: args.batch_size,
+    };
+    let report = run_simulation(&config).map_err(|e| CliError::user(e.to_string()))?;
+
+    let gini = report.gini();
+    let recipients = report
+        .recipients
+        .iter()
+        .take(args.top)
+        .map(|r| SimulationRecipient {
+            peer_id: r.peer_id.to_string(),
+            received: r.received,
+            synthesis_fees: r.synthesis_fees,
+            fair_share: r.fair_share,
+            share_percent: if report.total_distributed == 0 {
+                0.0
+            } else {
+                r.received as f64 * 100.0 / report.total_distributed as f64
+            },
+            payments: r.payments,
+        })
+        .collect();
+
+    let output = SimulationOutput {
+        seed: config.seed,
+        payments: config.payments,
+        contributors: config.contributors,
+        sources: config.sources,
+        derived: config.derived,
+        batches: report.batches,
+        max_roots: report.max_roots,
+        max_root_weight: report.max_root_weight,
+        total_paid: report.total_paid,
+        total_distributed: report.total_distributed,
+        total_settled: report.total_settled,
+        synthesis_fees: report.synthesis_fees,
+        root_pool: report.root_pool,
+        rounding_dust: report.rounding_dust,
+        max_dust: report.max_dust,
+        gini,
+        recipient_count: report.recipients.len(),
+        recipients,
+        violations: report.violations.iter().map(ToString::to_string).collect(),
+    };
+
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(payments: usize) -> SimulateArgs {
+        SimulateArgs {
+            seed: 1,
+            payments,
+            contributors: 8,
+            sources: 20,
+            derived: 10,
+            max_inputs: 3,
+            max_price: 1.0,
+            batch_size: 25,
+            top: 3,
+        }
+    }
+
+    #[test]
+    fn test_simulate_json_report() {
+        let output = simulate(OutputFormat::Json, args(500)).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(json["payments"], 500);
+        assert_eq!(json["batches"], 20);
+        assert_eq!(json["total_paid"], json["total_settled"]);
+        assert_eq!(json["recipients"].as_array().unwrap().len(), 3);
+        assert!(json["violations"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_simulate_rejects_invalid_config() {
+        let mut bad = args(10);
+        bad.batch_size = 0;
+        assert!(simulate(OutputFormat::Human, bad).is_err());
+
+        let mut bad = args(10);
+        bad.max_price = 0.0;
+        assert!(simulate(OutputFormat::Human, bad).is_err());
+    }
+}
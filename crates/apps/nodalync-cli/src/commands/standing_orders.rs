@@ -0,0 +1,193 @@
+//! Standing order commands.
+
+use nodalync_store::{StandingOrder, StandingOrderPurchase};
+
+use crate::config::{hbar_to_tinybars, CliConfig};
+use crate::context::NodeContext;
+use crate::error::CliResult;
+use crate::output::{
+    OutputFormat, Render, StandingOrderInfo, StandingOrderPurchaseInfo,
+    StandingOrderPurchasesOutput, StandingOrderRemoveOutput, StandingOrdersOutput,
+};
+
+fn order_info(order: StandingOrder) -> StandingOrderInfo {
+    StandingOrderInfo {
+        id: order.id,
+        tag: order.tag,
+        max_price: order.max_price,
+        min_reputation: order.min_reputation,
+        budget: order.budget,
+        spent: order.spent,
+        created_at: order.created_at,
+    }
+}
+
+fn purchase_info(purchase: StandingOrderPurchase) -> StandingOrderPurchaseInfo {
+    StandingOrderPurchaseInfo {
+        hash: purchase.content_hash.to_string(),
+        order_id: purchase.order_id,
+        title: purchase.title,
+        amount: purchase.amount,
+        purchased_at: purchase.purchased_at,
+    }
+}
+
+/// Execute the standing-orders add command.
+///
+/// Prices are in HBAR. Shows all orders afterwards.
+pub fn add_standing_order(
+    config: CliConfig,
+    format: OutputFormat,
+    tag: &str,
+    max_price: f64,
+    min_reputation: Option<i64>,
+    budget: Option<f64>,
+) -> CliResult<String> {
+    let enabled = config.ops_config()?.standing_orders.enabled;
+    let ctx = NodeContext::local(config)?;
+
+    let order = ctx.ops.add_standing_order(
+        tag,
+        hbar_to_tinybars(max_price),
+        min_reputation,
+        budget.map(hbar_to_tinybars),
+    )?;
+
+    let output = StandingOrdersOutput {
+        enabled,
+        added: Some(order.id),
+        orders: ctx
+            .ops
+            .list_standing_orders()?
+            .into_iter()
+            .map(order_info)
+            .collect(),
+    };
+
+    Ok(output.render(format))
+}
+
+/// Execute the standing-orders list command.
+pub fn list_standing_orders(config: CliConfig, format: OutputFormat) -> CliResult<String> {
+    let enabled = config.ops_config()?.standing_orders.enabled;
+    let ctx = NodeContext::local_read_only(config)?;
+
+    let output = StandingOrdersOutput {
+        enabled,
+        added: None,
+        orders: ctx
+            .ops
+            .list_standing_orders()?
+            .into_iter()
+            .map(order_info)
+            .collect(),
+    };
+
+    Ok(output.render(format))
+}
+
+/// Execute the standing-orders remove command.
+pub fn remove_standing_order(
+    config: CliConfig,
+    format: OutputFormat,
+    id: u64,
+) -> CliResult<String> {
+    let ctx = NodeContext::local(config)?;
+    let removed = ctx.ops.remove_standing_order(id)?;
+
+    Ok(StandingOrderRemoveOutput { id, removed }.render(format))
+}
+
+/// Execute the standing-orders purchases command.
+pub fn standing_order_purchases(
+    config: CliConfig,
+    format: OutputFormat,
+    order: Option<u64>,
+    limit: u32,
+) -> CliResult<String> {
+    let ctx = NodeContext::local_read_only(config)?;
+
+    let output = StandingOrderPurchasesOutput {
+        purchases: ctx
+            .ops
+            .standing_order_purchases(order, limit)?
+            .into_iter()
+            .map(purchase_info)
+            .collect(),
+    };
+
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use nodalync_crypto::content_hash;
+    use nodalync_store::StandingOrderStore;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config
+    }
+
+    #[test]
+    fn test_standing_orders() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let human = list_standing_orders(config.clone(), OutputFormat::Human).unwrap();
+        assert!(human.contains("No standing orders"));
+        assert!(human.contains("Not running"));
+
+        let json = add_standing_order(
+            config.clone(),
+            OutputFormat::Json,
+            "Science",
+            0.5,
+            None,
+            Some(2.0),
+        )
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let id = value["added"].as_u64().unwrap();
+        assert_eq!(value["orders"][0]["tag"], "science");
+        assert_eq!(value["orders"][0]["max_price"], 50_000_000);
+        assert_eq!(value["orders"][0]["budget"], 200_000_000);
+
+        {
+            let ctx = NodeContext::local(config.clone()).unwrap();
+            ctx.ops
+                .state
+                .standing_orders
+                .record_purchase(&StandingOrderPurchase {
+                    content_hash: content_hash(b"bought"),
+                    order_id: id,
+                    title: "Bought".to_string(),
+                    amount: 10,
+                    purchased_at: 1_000,
+                })
+                .unwrap();
+        }
+
+        let json =
+            standing_order_purchases(config.clone(), OutputFormat::Json, Some(id), 20).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["purchases"][0]["title"], "Bought");
+
+        let json = remove_standing_order(config.clone(), OutputFormat::Json, id).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["removed"], true);
+        let json = list_standing_orders(config, OutputFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(value["orders"].as_array().unwrap().is_empty());
+    }
+}
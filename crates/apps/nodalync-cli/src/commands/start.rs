@@ -70,6 +70,45 @@ async fn maybe_auto_deposit(
     }
 }
 
+/// Recover channel state after a restart.
+///
+/// Rolls channels forward to their latest write-ahead checkpoint and resyncs
+/// channels with nonce gaps against their counterparties. Failures are logged
+/// and do not prevent the node from starting.
+async fn recover_channels(ctx: &mut NodeContext) {
+    let recovered = match ctx.ops.recover_payment_channels() {
+        Ok(recovered) => recovered,
+        Err(e) => {
+            warn!(error = %e, "Channel recovery failed, continuing anyway");
+            return;
+        }
+    };
+
+    for recovery in recovered.iter().filter(|r| r.needs_resync()) {
+        match ctx.ops.resync_payment_channel(&recovery.peer).await {
+            Ok(changed) => info!(
+                channel_id = %recovery.channel_id,
+                changed = changed,
+                "Channel resynced with peer"
+            ),
+            Err(e) => warn!(
+                channel_id = %recovery.channel_id,
+                error = %e,
+                "Channel resync failed"
+            ),
+        }
+    }
+}
+
+/// Announce the content of primaries we serve as a replica.
+///
+/// Failures are logged and do not prevent the node from starting.
+async fn announce_replicas(ctx: &NodeContext) {
+    if let Err(e) = ctx.ops.announce_replicated_content().await {
+        warn!(error = %e, "Announcing replicated content failed");
+    }
+}
+
 /// Execute the start command (foreground mode only).
 ///
 /// For daemon mode, use `start_daemon_sync` which must be called
@@ -127,6 +166,10 @@ pub async fn start(
         network.subscribe_announcements().await?;
     }
 
+    // Recover channel state left behind by a crash
+    recover_channels(&mut ctx).await;
+    announce_replicas(&ctx).await;
+
     // Get info for output
     let listen_addresses = config.network.listen_addresses.clone();
     let connected_peers = ctx.connected_peers() as u32;
@@ -149,6 +192,9 @@ pub async fn start(
     if health {
         println!("Health endpoint: http://0.0.0.0:{}/health", health_port);
     }
+    if config.network.bootstrap_mode {
+        println!("Bootstrap mode: payments and content serving disabled");
+    }
     println!("\nPress Ctrl+C to stop the node...\n");
 
     // Set up shutdown signal handler
@@ -180,12 +226,15 @@ pub async fn start(
 /// IMPORTANT: This function MUST be called BEFORE any tokio runtime is created.
 /// It forks the process first, then creates a fresh tokio runtime in the child.
 /// This avoids the "cannot start runtime from within runtime" panic.
+/// Logging is initialized in the child too, since trace export runs on a
+/// thread that wouldn't survive the fork.
 #[cfg(unix)]
 pub fn start_daemon_sync(
     config: CliConfig,
     _format: OutputFormat,
     health: bool,
     health_port: u16,
+    verbose: bool,
 ) -> CliResult<String> {
     use daemonize::Daemonize;
     use std::fs::File;
@@ -234,6 +283,11 @@ pub fn start_daemon_sync(
     match daemonize.start() {
         Ok(()) => {
             // We are now in the child process (daemon)
+            if let Err(e) = crate::logging::init(&config.logging, Some(&base_dir), verbose) {
+                eprintln!("Failed to initialize logging: {}", e);
+                std::process::exit(1);
+            }
+
             // Create a fresh Tokio runtime - this is safe because no runtime existed before fork
             let rt = tokio::runtime::Runtime::new()
                 .map_err(|e| CliError::config(format!("Failed to create runtime: {}", e)))?;
@@ -276,6 +330,10 @@ pub fn start_daemon_sync(
                     }
                 }
 
+                // Recover channel state left behind by a crash
+                recover_channels(&mut ctx).await;
+                announce_replicas(&ctx).await;
+
                 // Write PID file with start time (after successful init)
                 if let Err(e) = write_pid_file_with_start_time(&pid_path) {
                     eprintln!("Failed to write PID file: {}", e);
@@ -289,6 +347,9 @@ pub fn start_daemon_sync(
                 if health {
                     eprintln!("Health endpoint: http://0.0.0.0:{}/health", health_port);
                 }
+                if config.network.bootstrap_mode {
+                    eprintln!("Bootstrap mode: payments and content serving disabled");
+                }
 
                 // Set up shutdown signal handler
                 let shutdown_rx = shutdown_signal();
@@ -319,6 +380,7 @@ pub fn start_daemon_sync(
                 }
             });
 
+            crate::logging::shutdown();
             std::process::exit(0);
         }
         Err(e) => {
@@ -335,6 +397,7 @@ pub fn start_daemon_sync(
     _format: OutputFormat,
     _health: bool,
     _health_port: u16,
+    _verbose: bool,
 ) -> CliResult<String> {
     Err(CliError::user(
         "Daemon mode is only supported on Unix systems",
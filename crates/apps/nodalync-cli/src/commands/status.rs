@@ -1,16 +1,17 @@
 //! Show node status command.
 
-use nodalync_store::{ManifestFilter, ManifestStore, SettlementQueueStore};
-use nodalync_types::Visibility;
+use nodalync_ops::NodeStats;
 
 use crate::config::CliConfig;
 use crate::context::NodeContext;
 use crate::error::CliResult;
+use crate::health::HealthReport;
+use crate::ipc::{try_daemon_request, IpcRequest};
 use crate::node_runner::{
     calculate_uptime, check_existing_node, pid_file_path, read_start_time, read_status_file,
     status_file_path,
 };
-use crate::output::{OutputFormat, Render, StatusOutput};
+use crate::output::{AnnouncementStatsOutput, OutputFormat, Render, StatusOutput};
 
 /// Execute the status command.
 pub async fn status(config: CliConfig, format: OutputFormat) -> CliResult<String> {
@@ -19,52 +20,22 @@ pub async fn status(config: CliConfig, format: OutputFormat) -> CliResult<String
     // Check if a node is running via PID file
     let running_pid = check_existing_node(&base_dir);
 
+    // Ask the running node directly; it has live peer counts and owns the DB
+    if let Some(output) = try_daemon_request(&base_dir, format, IpcRequest::Status).await? {
+        return Ok(output);
+    }
+
     // Try to initialize local context (for content stats)
-    let ctx = NodeContext::local(config.clone()).ok();
+    let ctx = NodeContext::local_read_only(config.clone()).ok();
 
     // If no node is running, show stopped status with local stats
     if running_pid.is_none() {
-        let (shared, private, pending_payments, pending_amount, peer_id) =
-            if let Some(ref ctx) = ctx {
-                let shared = ctx
-                    .ops
-                    .state
-                    .manifests
-                    .list(ManifestFilter::default().with_visibility(Visibility::Shared))
-                    .map(|v| v.len() as u32)
-                    .unwrap_or(0);
-
-                let private = ctx
-                    .ops
-                    .state
-                    .manifests
-                    .list(ManifestFilter::default().with_visibility(Visibility::Private))
-                    .map(|v| v.len() as u32)
-                    .unwrap_or(0);
-
-                let pending = ctx.ops.state.settlement.get_pending().unwrap_or_default();
-                let pending_amount = ctx.ops.state.settlement.get_pending_total().unwrap_or(0);
-
-                (
-                    shared,
-                    private,
-                    pending.len() as u32,
-                    pending_amount,
-                    ctx.peer_id().to_string(),
-                )
-            } else {
-                (0, 0, 0, 0, "N/A".to_string())
-            };
-
-        let output = StatusOutput {
-            running: false,
-            peer_id,
-            uptime_secs: None,
-            connected_peers: 0,
-            shared_content: shared,
-            private_content: private,
-            pending_payments,
-            pending_amount,
+        let output = match ctx.as_ref().and_then(|ctx| ctx.ops.stats().ok()) {
+            Some(stats) => status_output(false, &stats, None, 0),
+            None => StatusOutput {
+                peer_id: "N/A".to_string(),
+                ..Default::default()
+            },
         };
         return Ok(output.render(format));
     }
@@ -81,54 +52,85 @@ pub async fn status(config: CliConfig, format: OutputFormat) -> CliResult<String
                 running: true,
                 peer_id: format!("PID {}", running_pid.unwrap()),
                 uptime_secs,
-                connected_peers: 0,
-                shared_content: 0,
-                private_content: 0,
-                pending_payments: 0,
-                pending_amount: 0,
+                ..Default::default()
             };
             return Ok(output.render(format));
         }
     };
 
-    // Count content by visibility
-    let shared_count = ctx
-        .ops
-        .state
-        .manifests
-        .list(ManifestFilter::default().with_visibility(Visibility::Shared))?
-        .len() as u32;
-
-    let private_count = ctx
-        .ops
-        .state
-        .manifests
-        .list(ManifestFilter::default().with_visibility(Visibility::Private))?
-        .len() as u32;
-
-    // Get pending payments
-    let pending = ctx.ops.state.settlement.get_pending()?;
-    let pending_amount = ctx.ops.state.settlement.get_pending_total()?;
-
-    // Get connected peers from the status file (written by the running node)
-    let connected_peers = read_status_file(&status_file_path(&base_dir))
-        .map(|s| s.connected_peers)
-        .unwrap_or(0);
+    // Get connected peers and health from the status file (written by the running node)
+    let runtime = read_status_file(&status_file_path(&base_dir));
+    let connected_peers = runtime.as_ref().map(|s| s.connected_peers).unwrap_or(0);
+    let health = runtime.and_then(|s| s.health);
 
-    let output = StatusOutput {
-        running: true,
-        peer_id: ctx.peer_id().to_string(),
+    // The announcement filter counters live in the node process
+    running_status(&ctx, format, uptime_secs, connected_peers, health, None)
+}
+
+/// Build the status of the node running in this process.
+///
+/// Used by the node's control interface to answer `status` requests.
+pub fn status_with_context(ctx: &NodeContext, format: OutputFormat) -> CliResult<String> {
+    let base_dir = ctx.config.base_dir();
+    let uptime_secs = read_start_time(&pid_file_path(&base_dir)).map(calculate_uptime);
+    let health = read_status_file(&status_file_path(&base_dir)).and_then(|s| s.health);
+    running_status(
+        ctx,
+        format,
         uptime_secs,
-        connected_peers,
-        shared_content: shared_count,
-        private_content: private_count,
-        pending_payments: pending.len() as u32,
-        pending_amount,
-    };
+        ctx.connected_peers() as u32,
+        health,
+        Some(ctx.ops.announcement_filter_stats().into()),
+    )
+}
 
+/// Render the status of a running node from its local state.
+fn running_status(
+    ctx: &NodeContext,
+    format: OutputFormat,
+    uptime_secs: Option<u64>,
+    connected_peers: u32,
+    health: Option<HealthReport>,
+    announcements: Option<AnnouncementStatsOutput>,
+) -> CliResult<String> {
+    let stats = ctx.ops.stats()?;
+    let output = StatusOutput {
+        health,
+        announcements,
+        ..status_output(true, &stats, uptime_secs, connected_peers)
+    };
     Ok(output.render(format))
 }
 
+/// Status output from the node's statistics.
+///
+/// Uptime comes from the PID file and connected peers from the running
+/// node, since this process may only have opened the node's state.
+fn status_output(
+    running: bool,
+    stats: &NodeStats,
+    uptime_secs: Option<u64>,
+    connected_peers: u32,
+) -> StatusOutput {
+    StatusOutput {
+        running,
+        peer_id: stats.peer_id.to_string(),
+        uptime_secs,
+        connected_peers,
+        shared_content: stats.content.shared as u32,
+        private_content: stats.content.private as u32,
+        pending_payments: stats.pending_settlement_count as u32,
+        pending_amount: stats.pending_settlement_total,
+        open_channels: stats.open_channels as u32,
+        channel_balance: stats.channel_balance,
+        total_earned: stats.total_earned,
+        storage_bytes: stats.storage_bytes(),
+        cache_hit_rate: stats.cache_hit_rate(),
+        health: None,
+        announcements: None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -144,14 +146,34 @@ mod tests {
             private_content: 2,
             pending_payments: 3,
             pending_amount: 100_000_000,
+            open_channels: 2,
+            channel_balance: 500_000_000,
+            total_earned: 42_000_000,
+            storage_bytes: 3 * 1024 * 1024,
+            cache_hit_rate: 0.75,
+            health: None,
+            announcements: Some(
+                nodalync_ops::AnnouncementFilterStats {
+                    accepted: 40,
+                    dropped_rate_limited: 3,
+                    dropped_title: 2,
+                    collapsed_duplicates: 1,
+                    ..Default::default()
+                }
+                .into(),
+            ),
         };
 
         let human = output.render(OutputFormat::Human);
         assert!(human.contains("running"));
         assert!(human.contains("shared"));
+        assert!(human.contains("40 accepted, 5 dropped, 1 collapsed"));
+        assert!(human.contains("2 open"));
+        assert!(human.contains("3.0 MB, manifest cache 75% hits"));
 
         let json = output.render(OutputFormat::Json);
         assert!(json.contains("\"running\": true"));
+        assert!(json.contains("\"dropped_rate_limited\": 3"));
     }
 
     #[test]
@@ -165,9 +187,55 @@ mod tests {
             private_content: 0,
             pending_payments: 0,
             pending_amount: 0,
+            health: None,
+            announcements: None,
+            ..Default::default()
         };
 
         let human = output.render(OutputFormat::Human);
         assert!(human.contains("stopped"));
     }
+
+    #[test]
+    fn test_status_output_with_health() {
+        use crate::health::{Component, ComponentHealth, HealthState};
+
+        let mut components = std::collections::BTreeMap::new();
+        components.insert(
+            Component::Network,
+            ComponentHealth {
+                state: HealthState::Degraded,
+                detail: Some("0 peers".to_string()),
+                last_error: None,
+                last_error_at: None,
+            },
+        );
+        let output = StatusOutput {
+            running: true,
+            peer_id: "ndl1abc123".to_string(),
+            uptime_secs: Some(60),
+            connected_peers: 0,
+            shared_content: 0,
+            private_content: 0,
+            pending_payments: 0,
+            pending_amount: 0,
+            health: Some(HealthReport {
+                status: HealthState::Degraded,
+                uptime_secs: 60,
+                connected_peers: 0,
+                components,
+                last_error: None,
+            }),
+            announcements: None,
+            ..Default::default()
+        };
+
+        let human = output.render(OutputFormat::Human);
+        assert!(human.contains("Health:"));
+        assert!(human.contains("network"));
+        assert!(human.contains("0 peers"));
+
+        let json = output.render(OutputFormat::Json);
+        assert!(json.contains("\"degraded\""));
+    }
 }
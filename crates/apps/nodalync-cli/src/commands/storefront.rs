@@ -0,0 +1,159 @@
+//! Storefront command.
+
+use nodalync_crypto::peer_id_to_string;
+use nodalync_wire::Storefront;
+
+use super::channel::parse_peer_id;
+use crate::config::CliConfig;
+use crate::context::NodeContext;
+use crate::error::{CliError, CliResult};
+use crate::output::{OutputFormat, Render, StorefrontItemInfo, StorefrontOutput};
+use crate::prompt::get_identity_password;
+
+/// Execute the storefront command.
+///
+/// Fetches a publisher's storefront from the DHT, or without a publisher
+/// shows the storefront our shared content makes, storing it in the DHT
+/// with `publish`. `category` limits the items shown.
+pub async fn storefront(
+    config: CliConfig,
+    format: OutputFormat,
+    publisher: Option<&str>,
+    category: Option<&str>,
+    publish: bool,
+) -> CliResult<String> {
+    let storefront = match publisher {
+        Some(publisher_str) => {
+            let publisher = parse_peer_id(publisher_str)?;
+            let ctx = NodeContext::with_network(config).await?;
+            ctx.bootstrap().await?;
+            ctx.ops
+                .fetch_storefront(&publisher)
+                .await?
+                .ok_or_else(|| CliError::NotFound(format!("storefront of {}", publisher_str)))?
+        }
+        None if publish => {
+            let ctx = NodeContext::with_network(config).await?;
+            ctx.bootstrap().await?;
+            ctx.ops.publish_storefront().await?
+        }
+        None => {
+            let storefront_config = config.storefront.ops_config()?;
+            let mut ctx = NodeContext::local(config)?;
+            // Local contexts run with default ops settings; preview ours
+            ctx.ops.config.storefront = storefront_config;
+            load_signing_key(&mut ctx)?;
+            ctx.ops.build_storefront()?
+        }
+    };
+
+    Ok(storefront_output(storefront, category, publish).render(format))
+}
+
+/// Load the private key; storefronts are signed by their publisher.
+fn load_signing_key(ctx: &mut NodeContext) -> CliResult<()> {
+    let password = get_identity_password()?;
+    let (private_key, _) = ctx.ops.state.identity.load(&password).map_err(|e| {
+        if matches!(e, nodalync_store::StoreError::Encryption(_)) {
+            CliError::User(e.to_string())
+        } else {
+            CliError::from(e)
+        }
+    })?;
+    ctx.ops.set_private_key(private_key);
+    Ok(())
+}
+
+fn storefront_output(
+    storefront: Storefront,
+    category: Option<&str>,
+    published: bool,
+) -> StorefrontOutput {
+    StorefrontOutput {
+        publisher: peer_id_to_string(&storefront.publisher),
+        name: storefront.name,
+        created_at: storefront.created_at,
+        categories: storefront.categories,
+        items: storefront
+            .items
+            .into_iter()
+            .filter(|item| category.is_none() || item.category.as_deref() == category)
+            .map(|item| StorefrontItemInfo {
+                hash: item.hash.to_string(),
+                title: item.title,
+                content_type: format!("{:?}", item.content_type),
+                price: item.price,
+                category: item.category,
+                featured: item.featured,
+            })
+            .collect(),
+        published,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use crate::commands::publish::publish as publish_content;
+    use nodalync_types::Visibility;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_own_storefront() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let result = storefront(config.clone(), OutputFormat::Human, None, None, false)
+            .await
+            .unwrap();
+        assert!(result.contains("No items"));
+
+        let file = temp_dir.path().join("owls.txt");
+        std::fs::write(&file, "Notes on owls.").unwrap();
+        publish_content(
+            config.clone(),
+            OutputFormat::Json,
+            &file,
+            Some(1.0),
+            Visibility::Shared,
+            Some("Owls".to_string()),
+            None,
+            None,
+            None,
+            None,
+            vec!["birds".to_string()],
+        )
+        .await
+        .unwrap();
+
+        config.storefront.name = "Field Guides".to_string();
+        let result = storefront(config.clone(), OutputFormat::Json, None, None, false)
+            .await
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(value["name"], "Field Guides");
+        assert_eq!(value["categories"][0], "birds");
+        assert_eq!(value["items"][0]["title"], "Owls");
+        assert_eq!(value["published"], false);
+
+        let result = storefront(config, OutputFormat::Json, None, Some("plants"), false)
+            .await
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(value["items"].as_array().unwrap().len(), 0);
+    }
+}
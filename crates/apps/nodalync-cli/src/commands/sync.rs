@@ -0,0 +1,206 @@
+//! Cross-device sync commands.
+
+use std::path::Path;
+
+use nodalync_ops::{SyncBundle, SyncImport};
+use nodalync_wire::{decode_payload, encode_payload, EncryptedSyncBundle};
+
+use super::channel::parse_peer_id;
+use crate::config::CliConfig;
+use crate::context::NodeContext;
+use crate::error::{CliError, CliResult};
+use crate::output::{OutputFormat, Render, SyncConflictInfo, SyncExportOutput, SyncImportOutput};
+use crate::prompt::get_identity_password;
+
+/// Export our identity and channel state to an encrypted file.
+pub fn export_sync(config: CliConfig, format: OutputFormat, file: &Path) -> CliResult<String> {
+    let password = get_identity_password()?;
+    let mut ctx = NodeContext::local(config)?;
+    load_private_key(&mut ctx, &password)?;
+
+    let bundle = ctx.ops.export_sync_bundle(&password)?;
+    let bytes = encode_payload(&bundle)
+        .map_err(|e| CliError::User(format!("Failed to encode sync bundle: {}", e)))?;
+    std::fs::write(file, &bytes)?;
+
+    Ok(export_output("exported", &bundle, file.display().to_string()).render(format))
+}
+
+/// Import a sync bundle file exported by another device.
+///
+/// On a device without an identity, the bundle's key becomes this device's
+/// identity, encrypted under the same password.
+pub fn import_sync(config: CliConfig, format: OutputFormat, file: &Path) -> CliResult<String> {
+    let password = get_identity_password()?;
+    let bytes = std::fs::read(file)?;
+    let bundle: EncryptedSyncBundle =
+        decode_payload(&bytes).map_err(|e| CliError::User(format!("Not a sync bundle: {}", e)))?;
+
+    let state = NodeContext::for_init(config.clone())?;
+    let identity_installed = if state.identity.exists() {
+        if state.identity.peer_id()? != bundle.owner {
+            return Err(CliError::User(format!(
+                "Sync bundle belongs to {}, not this node's identity",
+                bundle.owner
+            )));
+        }
+        false
+    } else {
+        let private_key = SyncBundle::open(&bundle, &password)?.private_key();
+        state
+            .identity
+            .store_keypair(&private_key, &private_key.public_key(), &password)?;
+        true
+    };
+    drop(state);
+
+    let ctx = NodeContext::local(config)?;
+    let import = ctx.ops.import_sync_bundle(&bundle, &password)?;
+    Ok(import_output(
+        "imported",
+        &ctx,
+        file.display().to_string(),
+        identity_installed,
+        import,
+    )
+    .render(format))
+}
+
+/// Export a sync bundle and leave it with a peer for our other devices.
+pub async fn push_sync(
+    config: CliConfig,
+    format: OutputFormat,
+    peer_str: &str,
+) -> CliResult<String> {
+    let peer = parse_peer_id(peer_str)?;
+    let password = get_identity_password()?;
+
+    let ctx = NodeContext::with_network(config).await?;
+    ctx.bootstrap().await?;
+
+    let bundle = ctx.ops.push_sync_bundle(&peer, &password).await?;
+    Ok(export_output("pushed", &bundle, peer.to_string()).render(format))
+}
+
+/// Pull the sync bundle a peer holds for us and import it.
+pub async fn pull_sync(
+    config: CliConfig,
+    format: OutputFormat,
+    peer_str: &str,
+) -> CliResult<String> {
+    let peer = parse_peer_id(peer_str)?;
+    let password = get_identity_password()?;
+
+    let ctx = NodeContext::with_network(config).await?;
+    ctx.bootstrap().await?;
+
+    let import = ctx.ops.pull_sync_bundle(&peer, &password).await?;
+    Ok(import_output("pulled", &ctx, peer.to_string(), false, import).render(format))
+}
+
+/// Load the private key; it goes into the bundle.
+fn load_private_key(ctx: &mut NodeContext, password: &str) -> CliResult<()> {
+    let (private_key, _) = ctx.ops.state.identity.load(password).map_err(|e| {
+        if matches!(e, nodalync_store::StoreError::Encryption(_)) {
+            CliError::User(e.to_string())
+        } else {
+            CliError::from(e)
+        }
+    })?;
+    ctx.ops.set_private_key(private_key);
+    Ok(())
+}
+
+fn export_output(
+    operation: &str,
+    bundle: &EncryptedSyncBundle,
+    destination: String,
+) -> SyncExportOutput {
+    SyncExportOutput {
+        operation: operation.to_string(),
+        peer_id: bundle.owner.to_string(),
+        destination,
+        created_at: bundle.created_at,
+        bytes: bundle.ciphertext.len(),
+    }
+}
+
+fn import_output(
+    operation: &str,
+    ctx: &NodeContext,
+    source: String,
+    identity_installed: bool,
+    import: SyncImport,
+) -> SyncImportOutput {
+    SyncImportOutput {
+        operation: operation.to_string(),
+        peer_id: ctx.peer_id().to_string(),
+        source,
+        identity_installed,
+        created_at: import.created_at,
+        channels_added: import.channels_added,
+        channels_updated: import.channels_updated,
+        channels_unchanged: import.channels_unchanged,
+        conflicts: import
+            .conflicts
+            .iter()
+            .map(|c| SyncConflictInfo {
+                peer_id: c.peer_id.to_string(),
+                local_nonce: c.local_nonce,
+                bundle_nonce: c.bundle_nonce,
+            })
+            .collect(),
+        receipts_added: import.receipts_added,
+        accounts_registered: import.accounts_registered,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    #[test]
+    fn test_export_and_import_to_new_device() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let laptop_dir = TempDir::new().unwrap();
+        let laptop = setup_config(&laptop_dir);
+        init(laptop.clone(), OutputFormat::Human, false).unwrap();
+        let file = laptop_dir.path().join("bundle.sync");
+
+        let output = export_sync(laptop.clone(), OutputFormat::Json, &file).unwrap();
+        let exported: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(exported["operation"], "exported");
+        assert!(file.exists());
+
+        // A fresh device takes the identity from the bundle
+        let phone_dir = TempDir::new().unwrap();
+        let phone = setup_config(&phone_dir);
+        let output = import_sync(phone.clone(), OutputFormat::Json, &file).unwrap();
+        let imported: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(imported["identity_installed"], true);
+        assert_eq!(imported["peer_id"], exported["peer_id"]);
+
+        // Importing again keeps it
+        let output = import_sync(phone, OutputFormat::Human, &file).unwrap();
+        assert!(output.contains("Channels:"));
+
+        // Another identity refuses the bundle
+        let other_dir = TempDir::new().unwrap();
+        let other = setup_config(&other_dir);
+        init(other.clone(), OutputFormat::Human, false).unwrap();
+        assert!(import_sync(other, OutputFormat::Human, &file).is_err());
+    }
+}
@@ -40,7 +40,7 @@ pub async fn synthesize(
     let content = std::fs::read(output_file)?;
 
     // Initialize context
-    let mut ctx = NodeContext::with_network(config.clone()).await?;
+    let ctx = NodeContext::with_network(config.clone()).await?;
 
     // Verify sources exist and are owned/queried
     for source in &sources {
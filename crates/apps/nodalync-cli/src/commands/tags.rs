@@ -0,0 +1,86 @@
+//! List and autocomplete tags command.
+
+use crate::config::CliConfig;
+use crate::context::NodeContext;
+use crate::error::CliResult;
+use crate::output::{OutputFormat, Render, TagSummary, TagsOutput};
+
+/// Execute the tags command.
+///
+/// With a prefix, suggests up to `limit` registered tags, most used first.
+/// Without one, lists every registered tag.
+pub fn tags(
+    config: CliConfig,
+    format: OutputFormat,
+    prefix: Option<&str>,
+    limit: u32,
+) -> CliResult<String> {
+    let ctx = NodeContext::local_read_only(config)?;
+
+    let tags = match prefix {
+        Some(prefix) => ctx.ops.autocomplete_tags(prefix, limit)?,
+        None => ctx.ops.list_tags()?,
+    };
+
+    let output = TagsOutput {
+        prefix: prefix.map(str::to_string),
+        tags: tags
+            .into_iter()
+            .map(|t| TagSummary {
+                tag: t.tag,
+                parent: t.parent,
+                content_count: t.content_count,
+            })
+            .collect(),
+    };
+
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use nodalync_types::Metadata;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config
+    }
+
+    #[test]
+    fn test_tags_list_and_autocomplete() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let output = tags(config.clone(), OutputFormat::Human, None, 10).unwrap();
+        assert!(output.contains("No tags yet"));
+
+        {
+            let ctx = NodeContext::local(config.clone()).unwrap();
+            let metadata = Metadata::new("Cells", 5).with_tags(vec!["Science/Biology".to_string()]);
+            ctx.ops.create_content(b"cells", metadata).unwrap();
+        }
+
+        let output = tags(config.clone(), OutputFormat::Human, None, 10).unwrap();
+        assert!(output.contains("science/biology"));
+        assert!(output.contains("(1 item)"));
+
+        let output = tags(config.clone(), OutputFormat::Json, Some("bio"), 10).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["tags"][0]["tag"], "science/biology");
+        assert_eq!(json["tags"][0]["parent"], "science");
+        assert_eq!(json["tags"].as_array().unwrap().len(), 1);
+
+        let output = tags(config, OutputFormat::Human, Some("zoo"), 10).unwrap();
+        assert!(output.contains("No tags matching \"zoo\""));
+    }
+}
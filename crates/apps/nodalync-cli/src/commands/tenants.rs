@@ -0,0 +1,256 @@
+//! Tenant commands.
+
+use nodalync_ops::TenantReport;
+use nodalync_store::{Tenant, TenantPurchase};
+
+use crate::config::{hbar_to_tinybars, CliConfig};
+use crate::context::{parse_hash, NodeContext};
+use crate::error::CliResult;
+use crate::output::{
+    OutputFormat, Render, TenantAssignOutput, TenantInfo, TenantKeyOutput, TenantPurchaseInfo,
+    TenantPurchasesOutput, TenantRemoveOutput, TenantReportOutput, TenantsOutput,
+};
+
+fn tenant_info(tenant: Tenant) -> TenantInfo {
+    TenantInfo {
+        id: tenant.id,
+        name: tenant.name,
+        budget: tenant.budget,
+        spent: tenant.spent,
+        created_at: tenant.created_at,
+    }
+}
+
+fn purchase_info(purchase: TenantPurchase) -> TenantPurchaseInfo {
+    TenantPurchaseInfo {
+        payment_id: purchase.payment_id.to_string(),
+        tenant: purchase.tenant,
+        hash: purchase.content_hash.to_string(),
+        amount: purchase.amount,
+        purchased_at: purchase.purchased_at,
+    }
+}
+
+fn report_output(report: TenantReport) -> TenantReportOutput {
+    TenantReportOutput {
+        tenant: tenant_info(report.tenant),
+        purchases: report.purchases,
+        content: report.content.iter().map(|hash| hash.to_string()).collect(),
+        earned: report.earned,
+    }
+}
+
+/// Execute the tenants create command.
+///
+/// The budget is in HBAR. Shows the new tenant's API key.
+pub fn create_tenant(
+    config: CliConfig,
+    format: OutputFormat,
+    id: &str,
+    name: Option<&str>,
+    budget: Option<f64>,
+) -> CliResult<String> {
+    let ctx = NodeContext::local(config)?;
+    let (tenant, api_key) =
+        ctx.ops
+            .create_tenant(id, name.unwrap_or(id), budget.map(hbar_to_tinybars))?;
+
+    let output = TenantKeyOutput {
+        tenant: tenant_info(tenant),
+        api_key,
+        created: true,
+    };
+    Ok(output.render(format))
+}
+
+/// Execute the tenants list command.
+pub fn list_tenants(config: CliConfig, format: OutputFormat) -> CliResult<String> {
+    let ctx = NodeContext::local_read_only(config)?;
+
+    let output = TenantsOutput {
+        tenants: ctx
+            .ops
+            .list_tenants()?
+            .into_iter()
+            .map(tenant_info)
+            .collect(),
+    };
+    Ok(output.render(format))
+}
+
+/// Execute the tenants show command.
+pub fn show_tenant(config: CliConfig, format: OutputFormat, id: &str) -> CliResult<String> {
+    let ctx = NodeContext::local_read_only(config)?;
+    Ok(report_output(ctx.ops.tenant_report(id)?).render(format))
+}
+
+/// Execute the tenants budget command.
+///
+/// The budget is in HBAR; `None` removes it.
+pub fn set_tenant_budget(
+    config: CliConfig,
+    format: OutputFormat,
+    id: &str,
+    budget: Option<f64>,
+) -> CliResult<String> {
+    let ctx = NodeContext::local(config)?;
+    ctx.ops
+        .set_tenant_budget(id, budget.map(hbar_to_tinybars))?;
+    Ok(report_output(ctx.ops.tenant_report(id)?).render(format))
+}
+
+/// Execute the tenants rotate-key command.
+pub fn rotate_tenant_key(config: CliConfig, format: OutputFormat, id: &str) -> CliResult<String> {
+    let ctx = NodeContext::local(config)?;
+    let api_key = ctx.ops.rotate_tenant_key(id)?;
+
+    let output = TenantKeyOutput {
+        tenant: tenant_info(ctx.ops.get_tenant(id)?),
+        api_key,
+        created: false,
+    };
+    Ok(output.render(format))
+}
+
+/// Execute the tenants remove command.
+pub fn remove_tenant(config: CliConfig, format: OutputFormat, id: &str) -> CliResult<String> {
+    let ctx = NodeContext::local(config)?;
+    let removed = ctx.ops.remove_tenant(id)?;
+
+    Ok(TenantRemoveOutput {
+        id: id.to_string(),
+        removed,
+    }
+    .render(format))
+}
+
+/// Execute the tenants assign command.
+pub fn assign_tenant_content(
+    config: CliConfig,
+    format: OutputFormat,
+    hash_str: &str,
+    tenant: Option<&str>,
+) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+    let ctx = NodeContext::local(config)?;
+    ctx.ops.assign_content_to_tenant(&hash, tenant)?;
+
+    Ok(TenantAssignOutput {
+        hash: hash.to_string(),
+        tenant: tenant.map(str::to_string),
+    }
+    .render(format))
+}
+
+/// Execute the tenants purchases command.
+pub fn tenant_purchases(
+    config: CliConfig,
+    format: OutputFormat,
+    tenant: Option<&str>,
+    limit: u32,
+) -> CliResult<String> {
+    let ctx = NodeContext::local_read_only(config)?;
+
+    let output = TenantPurchasesOutput {
+        purchases: ctx
+            .ops
+            .tenant_purchases(tenant, limit)?
+            .into_iter()
+            .map(purchase_info)
+            .collect(),
+    };
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use nodalync_crypto::content_hash;
+    use nodalync_ops::TENANT_KEY_PREFIX;
+    use nodalync_store::TenantStore;
+    use nodalync_types::Metadata;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config
+    }
+
+    #[test]
+    fn test_tenants() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let human = list_tenants(config.clone(), OutputFormat::Human).unwrap();
+        assert!(human.contains("No tenants"));
+
+        let json = create_tenant(
+            config.clone(),
+            OutputFormat::Json,
+            "acme",
+            Some("Acme Corp"),
+            Some(2.0),
+        )
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let key = value["api_key"].as_str().unwrap().to_string();
+        assert!(key.starts_with(TENANT_KEY_PREFIX));
+        assert_eq!(value["tenant"]["budget"], 200_000_000);
+
+        let human = rotate_tenant_key(config.clone(), OutputFormat::Human, "acme").unwrap();
+        assert!(human.contains(TENANT_KEY_PREFIX));
+        assert!(!human.contains(&key));
+
+        let hash = {
+            let ctx = NodeContext::local(config.clone()).unwrap();
+            ctx.ops
+                .state
+                .tenants
+                .record_purchase(&TenantPurchase {
+                    payment_id: content_hash(b"receipt"),
+                    tenant: "acme".to_string(),
+                    content_hash: content_hash(b"bought"),
+                    amount: 10,
+                    purchased_at: 1_000,
+                })
+                .unwrap();
+            ctx.ops
+                .create_content(b"Acme report", Metadata::new("Report", 11))
+                .unwrap()
+        };
+
+        assign_tenant_content(
+            config.clone(),
+            OutputFormat::Human,
+            &hash.to_string(),
+            Some("acme"),
+        )
+        .unwrap();
+        let json = show_tenant(config.clone(), OutputFormat::Json, "acme").unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["tenant"]["spent"], 10);
+        assert_eq!(value["purchases"], 1);
+        assert_eq!(value["content"][0], hash.to_string());
+
+        let json = set_tenant_budget(config.clone(), OutputFormat::Json, "acme", None).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(value["tenant"].get("budget").is_none());
+
+        let json = tenant_purchases(config.clone(), OutputFormat::Json, Some("acme"), 20).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["purchases"][0]["amount"], 10);
+
+        let json = remove_tenant(config.clone(), OutputFormat::Json, "acme").unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["removed"], true);
+        assert!(show_tenant(config, OutputFormat::Json, "acme").is_err());
+    }
+}
@@ -0,0 +1,143 @@
+//! Free trial reads command.
+
+use nodalync_types::TrialPolicy;
+
+use crate::config::CliConfig;
+use crate::context::{parse_hash, NodeContext};
+use crate::error::{CliError, CliResult};
+use crate::output::{OutputFormat, Render, TrialOutput};
+
+/// Execute the trial command.
+///
+/// Offers `reads` free reads per peer when given, of only the first `bytes`
+/// bytes if set; stops offering them with `off`; and otherwise shows the
+/// current trial.
+pub fn trial(
+    config: CliConfig,
+    format: OutputFormat,
+    hash_str: &str,
+    reads: Option<u32>,
+    bytes: Option<u64>,
+    off: bool,
+) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+    let ctx = NodeContext::local(config)?;
+
+    let manifest = ctx
+        .ops
+        .get_content_manifest(&hash)?
+        .ok_or_else(|| CliError::NotFound(hash_str.to_string()))?;
+    if manifest.owner != ctx.peer_id() {
+        return Err(CliError::User("You don't own this content".to_string()));
+    }
+
+    if off {
+        ctx.ops.set_content_trial(&hash, None)?;
+    } else if let Some(reads) = reads {
+        let policy = TrialPolicy { reads, bytes };
+        ctx.ops.set_content_trial(&hash, Some(policy))?;
+    }
+
+    let manifest = ctx
+        .ops
+        .get_content_manifest(&hash)?
+        .ok_or_else(|| CliError::NotFound(hash_str.to_string()))?;
+
+    let output = TrialOutput {
+        hash: hash.to_string(),
+        title: manifest.metadata.title,
+        price: manifest.economics.price,
+        trial: manifest.access.trial,
+        readers: ctx.ops.trial_readers(&hash)?,
+    };
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use crate::commands::publish::publish;
+    use nodalync_crypto::content_hash;
+    use nodalync_types::Visibility;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_trial() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let text = "A report worth trying before buying.";
+        let file = temp_dir.path().join("report.txt");
+        std::fs::write(&file, text).unwrap();
+        publish(
+            config.clone(),
+            OutputFormat::Json,
+            &file,
+            Some(1.0),
+            Visibility::Shared,
+            Some("Report".to_string()),
+            None,
+            None,
+            None,
+            None,
+            vec![],
+        )
+        .await
+        .unwrap();
+        let hash = content_hash(text.as_bytes()).to_string();
+
+        let result = trial(
+            config.clone(),
+            OutputFormat::Human,
+            &hash,
+            None,
+            None,
+            false,
+        )
+        .unwrap();
+        assert!(result.contains("No free reads"));
+
+        let result = trial(
+            config.clone(),
+            OutputFormat::Json,
+            &hash,
+            Some(3),
+            Some(10),
+            false,
+        )
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(value["trial"]["reads"], 3);
+        assert_eq!(value["trial"]["bytes"], 10);
+        assert_eq!(value["readers"], 0);
+
+        // More reads than a trial may offer are refused
+        assert!(trial(
+            config.clone(),
+            OutputFormat::Json,
+            &hash,
+            Some(1_000),
+            None,
+            false
+        )
+        .is_err());
+
+        let result = trial(config, OutputFormat::Json, &hash, None, None, true).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(value.get("trial").is_none());
+    }
+}
@@ -31,7 +31,7 @@ pub fn update(
     let content = std::fs::read(file)?;
 
     // Initialize context
-    let mut ctx = NodeContext::local(config)?;
+    let ctx = NodeContext::local(config)?;
 
     // Get existing manifest
     let existing = ctx
@@ -0,0 +1,130 @@
+//! Content statistics and usage report commands.
+
+use crate::config::CliConfig;
+use crate::context::{parse_hash, NodeContext};
+use crate::error::{CliError, CliResult};
+use crate::output::{ContentStatsOutput, OutputFormat, Render, UsageReportOutput};
+
+/// Show access statistics and reported usage for local content.
+pub fn stats(config: CliConfig, format: OutputFormat, hash_str: &str) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+    let ctx = NodeContext::local_read_only(config)?;
+
+    let manifest = ctx
+        .ops
+        .get_content_manifest(&hash)?
+        .ok_or_else(|| CliError::NotFound(hash_str.to_string()))?;
+    let stats = ctx.ops.content_stats(&hash)?;
+
+    let output = ContentStatsOutput {
+        hash: hash.to_string(),
+        title: manifest.metadata.title,
+        previews: stats.previews,
+        queries: stats.queries,
+        free_queries: stats.free_queries,
+        unique_consumers: stats.unique_consumers,
+        unique_payers: stats.unique_payers,
+        revenue: stats.revenue,
+        first_access: stats.first_access,
+        last_access: stats.last_access,
+        usage_reports: stats.usage.reports,
+        bytes_read: stats.usage.bytes_read,
+        average_bytes_read: stats.usage.average_bytes_read(),
+        average_rating: stats.usage.average_rating(),
+        rated_reports: stats.usage.rated,
+        average_context_tokens: stats.usage.average_context_tokens(),
+    };
+    Ok(output.render(format))
+}
+
+/// Report how queried content was used back to its publisher.
+pub async fn report_usage(
+    config: CliConfig,
+    format: OutputFormat,
+    hash_str: &str,
+    bytes_read: u64,
+    context_tokens: Option<u32>,
+    rating: Option<u8>,
+) -> CliResult<String> {
+    let hash = parse_hash(hash_str)?;
+    if !config.usage_reports.send {
+        return Err(CliError::User(
+            "Usage reports are disabled; set usage_reports.send = true in the config".into(),
+        ));
+    }
+
+    let ctx = NodeContext::with_network(config).await?;
+    ctx.bootstrap().await?;
+
+    ctx.ops
+        .report_usage(&hash, bytes_read, context_tokens, rating)
+        .await?;
+
+    let output = UsageReportOutput {
+        hash: hash.to_string(),
+        bytes_read,
+        context_tokens,
+        rating,
+    };
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use crate::commands::publish::publish;
+    use nodalync_types::Visibility;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config.network.enabled = false;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_stats_and_report_opt_in() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        assert!(stats(config.clone(), OutputFormat::Human, "invalidhash").is_err());
+
+        let file = temp_dir.path().join("notes.txt");
+        std::fs::write(&file, "Usage statistics test content.").unwrap();
+        let output = publish(
+            config.clone(),
+            OutputFormat::Json,
+            &file,
+            Some(0.0),
+            Visibility::Shared,
+            Some("Notes".to_string()),
+            None,
+            None,
+            None,
+            None,
+            vec![],
+        )
+        .await
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let hash = json["hash"].as_str().unwrap().to_string();
+
+        let output = stats(config.clone(), OutputFormat::Json, &hash).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["title"], "Notes");
+        assert_eq!(json["usage_reports"], 0);
+        assert!(json["average_rating"].is_null());
+
+        // Sending is opt-in
+        let result = report_usage(config, OutputFormat::Human, &hash, 10, None, Some(5)).await;
+        assert!(matches!(result, Err(CliError::User(_))));
+    }
+}
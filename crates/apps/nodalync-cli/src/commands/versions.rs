@@ -1,18 +1,59 @@
 //! Show content versions command.
+//!
+//! Lists the version history and reports gaps and forks in the chain. With
+//! `--repair`, missing version manifests are fetched from the owner first.
+
+use nodalync_ops::VersionChainReport;
 
 use crate::config::CliConfig;
 use crate::context::{parse_hash, NodeContext};
 use crate::error::{CliError, CliResult};
-use crate::output::{OutputFormat, Render, VersionInfo, VersionsOutput};
+use crate::ipc::{try_daemon_request, IpcRequest};
+use crate::output::{
+    OutputFormat, Render, VersionForkInfo, VersionGapInfo, VersionInfo, VersionsOutput,
+};
 
 /// Execute the versions command.
-pub fn versions(config: CliConfig, format: OutputFormat, hash_str: &str) -> CliResult<String> {
+pub async fn versions(
+    config: CliConfig,
+    format: OutputFormat,
+    hash_str: &str,
+    repair: bool,
+) -> CliResult<String> {
+    if !repair {
+        let mut ctx = NodeContext::local_read_only(config)?;
+        return versions_with_context(&mut ctx, format, hash_str, false).await;
+    }
+
+    // Forward to the running node if there is one
+    if let Some(output) = try_daemon_request(
+        &config.base_dir(),
+        format,
+        IpcRequest::RepairVersions {
+            hash: hash_str.to_string(),
+        },
+    )
+    .await?
+    {
+        return Ok(output);
+    }
+
+    // Missing versions are fetched from the owner
+    let mut ctx = NodeContext::with_network(config).await?;
+    ctx.bootstrap().await?;
+    versions_with_context(&mut ctx, format, hash_str, true).await
+}
+
+/// Show (and optionally repair) versions using an existing node context.
+pub async fn versions_with_context(
+    ctx: &mut NodeContext,
+    format: OutputFormat,
+    hash_str: &str,
+    repair: bool,
+) -> CliResult<String> {
     // Parse hash
     let hash = parse_hash(hash_str)?;
 
-    // Initialize context
-    let ctx = NodeContext::local(config)?;
-
     // Get content manifest to find version root
     let manifest = ctx
         .ops
@@ -21,6 +62,14 @@ pub fn versions(config: CliConfig, format: OutputFormat, hash_str: &str) -> CliR
 
     let version_root = manifest.version.root;
 
+    // Check the chain, repairing it first if asked
+    let (chain, repaired) = if repair {
+        let repair = ctx.ops.repair_version_chain(&hash).await?;
+        (repair.report, Some(repair.fetched.len()))
+    } else {
+        (ctx.ops.verify_version_chain(&hash)?, None)
+    };
+
     // Get all versions
     let versions_list = ctx.ops.get_content_versions(&version_root)?;
 
@@ -28,8 +77,8 @@ pub fn versions(config: CliConfig, format: OutputFormat, hash_str: &str) -> CliR
         return Err(CliError::NotFound(hash_str.to_string()));
     }
 
-    // Find the latest version number
-    let max_version = versions_list.iter().map(|m| m.number).max().unwrap_or(1);
+    // The current version, with forks resolved by the fork policy
+    let current = ctx.ops.current_version(&version_root)?;
 
     // Convert to version info
     let version_infos: Vec<VersionInfo> = versions_list
@@ -39,18 +88,43 @@ pub fn versions(config: CliConfig, format: OutputFormat, hash_str: &str) -> CliR
             hash: m.hash.to_string(),
             timestamp: m.timestamp,
             visibility: format!("{:?}", m.visibility),
-            is_latest: m.number == max_version,
+            is_latest: Some(m.hash) == current,
         })
         .collect();
 
+    let (gaps, forks) = chain_issues(&chain);
     let output = VersionsOutput {
         version_root: version_root.to_string(),
         versions: version_infos,
+        gaps,
+        forks,
+        repaired,
     };
 
     Ok(output.render(format))
 }
 
+/// Convert a chain report's gaps and forks for output.
+fn chain_issues(chain: &VersionChainReport) -> (Vec<VersionGapInfo>, Vec<VersionForkInfo>) {
+    let gaps = chain
+        .gaps
+        .iter()
+        .map(|gap| VersionGapInfo {
+            missing: gap.missing.to_string(),
+            before_version: gap.number,
+        })
+        .collect();
+    let forks = chain
+        .forks
+        .iter()
+        .map(|fork| VersionForkInfo {
+            previous: fork.previous.to_string(),
+            branches: fork.branches.iter().map(|h| h.to_string()).collect(),
+        })
+        .collect();
+    (gaps, forks)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -66,8 +140,8 @@ mod tests {
         config
     }
 
-    #[test]
-    fn test_versions_not_found() {
+    #[tokio::test]
+    async fn test_versions_not_found() {
         std::env::set_var("NODALYNC_PASSWORD", "test_password");
 
         let temp_dir = TempDir::new().unwrap();
@@ -75,7 +149,39 @@ mod tests {
 
         init(config.clone(), OutputFormat::Human, false).unwrap();
 
-        let result = versions(config, OutputFormat::Human, "invalidhash");
+        let result = versions(config, OutputFormat::Human, "invalidhash", false).await;
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_versions_output_reports_gaps() {
+        let mut output = VersionsOutput {
+            version_root: "a1b2c3d4e5f60718293a".to_string(),
+            versions: vec![VersionInfo {
+                version: 3,
+                hash: "f0e1d2c3b4a596877869".to_string(),
+                timestamp: 1_700_000_000_000,
+                visibility: "Shared".to_string(),
+                is_latest: true,
+            }],
+            gaps: vec![VersionGapInfo {
+                missing: "0011223344556677889900".to_string(),
+                before_version: 3,
+            }],
+            forks: vec![],
+            repaired: None,
+        };
+        let human = output.render_human();
+        assert!(human.contains("Missing version before v3"));
+        assert!(human.contains("--repair"));
+
+        output.repaired = Some(1);
+        assert!(output
+            .render_human()
+            .contains("fetched 1 missing version(s)"));
+        let json = output.render_json();
+        assert!(json.contains("\"before_version\": 3"));
+        assert!(json.contains("\"repaired\": 1"));
+        assert!(!json.contains("forks"));
+    }
 }
@@ -13,12 +13,22 @@ pub async fn visibility(
     format: OutputFormat,
     hash_str: &str,
     new_visibility: Visibility,
+    groups: &[String],
 ) -> CliResult<String> {
-    // Parse hash
+    // Parse hash and group IDs
     let hash = parse_hash(hash_str)?;
+    let group_ids = groups
+        .iter()
+        .map(|g| parse_hash(g))
+        .collect::<CliResult<Vec<_>>>()?;
+    if !group_ids.is_empty() && new_visibility != Visibility::Unlisted {
+        return Err(CliError::User(
+            "Groups only apply to unlisted content".to_string(),
+        ));
+    }
 
     // Initialize context with network (needed for DHT operations)
-    let mut ctx = NodeContext::with_network(config).await?;
+    let ctx = NodeContext::with_network(config).await?;
 
     // Verify content exists
     let manifest = ctx
@@ -31,12 +41,20 @@ pub async fn visibility(
         return Err(CliError::User("You don't own this content".to_string()));
     }
 
+    // Admit group members before the content becomes visible
+    if !group_ids.is_empty() {
+        let mut access = manifest.access.clone();
+        access.groups = Some(group_ids);
+        ctx.ops.set_content_access(&hash, access)?;
+    }
+
     // Set visibility
     ctx.ops.set_content_visibility(&hash, new_visibility)?;
 
     let output = VisibilityOutput {
         hash: hash.to_string(),
         new_visibility: format!("{:?}", new_visibility),
+        groups: groups.to_vec(),
     };
 
     Ok(output.render(format))
@@ -51,6 +69,7 @@ mod tests {
         let output = VisibilityOutput {
             hash: "abc123".to_string(),
             new_visibility: "Shared".to_string(),
+            groups: vec![],
         };
 
         let human = output.render(OutputFormat::Human);
@@ -0,0 +1,105 @@
+//! Webhook delivery status command.
+
+use nodalync_store::WebhookDeliveryStatus;
+
+use crate::config::CliConfig;
+use crate::context::NodeContext;
+use crate::error::CliResult;
+use crate::output::{OutputFormat, Render, WebhookDeliveryInfo, WebhooksOutput};
+
+/// Execute the webhooks list command.
+///
+/// Shows recent deliveries newest first, with their attempts and the
+/// last error of those that failed.
+pub fn list_webhooks(
+    config: CliConfig,
+    format: OutputFormat,
+    status: Option<WebhookDeliveryStatus>,
+    limit: u32,
+) -> CliResult<String> {
+    let endpoints = config.ops_config()?.webhooks.endpoints.len();
+    let ctx = NodeContext::local_read_only(config)?;
+
+    let output = WebhooksOutput {
+        endpoints,
+        deliveries: ctx
+            .ops
+            .webhook_deliveries(status, limit)?
+            .into_iter()
+            .map(|d| WebhookDeliveryInfo {
+                id: d.id,
+                url: d.url,
+                event: d.event,
+                status: d.status.to_string(),
+                attempts: d.attempts,
+                created_at: d.created_at,
+                next_attempt_at: (d.status == WebhookDeliveryStatus::Pending)
+                    .then_some(d.next_attempt_at),
+                last_error: d.last_error,
+            })
+            .collect(),
+    };
+
+    Ok(output.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::init;
+    use nodalync_store::WebhookStore;
+    use tempfile::TempDir;
+
+    fn setup_config(temp_dir: &TempDir) -> CliConfig {
+        let mut config = CliConfig::default();
+        config.storage.content_dir = temp_dir.path().join("content");
+        config.storage.cache_dir = temp_dir.path().join("cache");
+        config.storage.database = temp_dir.path().join("nodalync.db");
+        config.identity.keyfile = temp_dir.path().join("identity").join("keypair.key");
+        config
+    }
+
+    #[test]
+    fn test_list_webhooks() {
+        std::env::set_var("NODALYNC_PASSWORD", "test_password");
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = setup_config(&temp_dir);
+        init(config.clone(), OutputFormat::Human, false).unwrap();
+
+        let human = list_webhooks(config.clone(), OutputFormat::Human, None, 20).unwrap();
+        assert!(human.contains("No webhook deliveries"));
+
+        {
+            let ctx = NodeContext::local(config.clone()).unwrap();
+            let id = ctx
+                .ops
+                .state
+                .webhooks
+                .enqueue("https://hooks.example", "payment_received", "{}", 1_000)
+                .unwrap();
+            ctx.ops
+                .state
+                .webhooks
+                .record_failure(id, "HTTP 500", 1_000, None)
+                .unwrap();
+        }
+
+        let json = list_webhooks(config.clone(), OutputFormat::Json, None, 20).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let deliveries = value["deliveries"].as_array().unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0]["status"], "failed");
+        assert_eq!(deliveries[0]["last_error"], "HTTP 500");
+
+        let pending = list_webhooks(
+            config,
+            OutputFormat::Json,
+            Some(WebhookDeliveryStatus::Pending),
+            20,
+        )
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&pending).unwrap();
+        assert!(value["deliveries"].as_array().unwrap().is_empty());
+    }
+}
@@ -8,7 +8,7 @@ use crate::prompt::get_identity_password;
 
 /// Execute the whoami command.
 pub fn whoami(config: CliConfig, format: OutputFormat) -> CliResult<String> {
-    let ctx = NodeContext::local(config.clone())?;
+    let ctx = NodeContext::local_read_only(config.clone())?;
 
     // Get public key
     let public_key = ctx.ops.state.identity.public_key()?;
@@ -28,6 +28,7 @@ pub fn whoami(config: CliConfig, format: OutputFormat) -> CliResult<String> {
         peer_id: ctx.peer_id().to_string(),
         libp2p_peer_id: libp2p_peer_id.to_string(),
         public_key: format!("0x{}", hex::encode(public_key.0)),
+        did: nodalync_crypto::did_from_public_key(&public_key),
         addresses: vec![], // Addresses populated when network is running
     };
 
@@ -77,6 +78,7 @@ mod tests {
         let output = result.unwrap();
         assert!(output.contains("PeerId"));
         assert!(output.contains("Public Key"));
+        assert!(output.contains("did:key:z6Mk"));
     }
 
     #[test]
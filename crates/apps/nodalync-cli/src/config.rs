@@ -1,9 +1,24 @@
 //! CLI configuration.
 
+use nodalync_crypto::peer_id_from_string;
+use nodalync_mcp::MetricsConfig;
+use nodalync_net::{DnsBootstrapConfig, RelayConfig, MAX_RELAY_HOPS};
+use nodalync_ops::{
+    AnnouncementFilterConfig, AnnouncementIngestConfig, AvailabilityConfig, BondConfig,
+    ChannelConfig, ClockSkewConfig, DisputeConfig, EntitlementConfig, FraudProofConfig,
+    ModerationConfig, NotificationConfig, OpsConfig, PopularityConfig, QosConfig,
+    QueryChallengeConfig, RebalanceConfig, RetentionConfig, SnapshotConfig, StandingOrderConfig,
+    StorefrontConfig, TopUpConfig, TrustCheck, TrustPolicy, TrustWeights, UsageReportConfig,
+};
+use nodalync_store::{DigestPeriod, RetentionCategory};
+use nodalync_valid::BondRequirements;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use crate::context::parse_hash;
 use crate::error::{CliError, CliResult};
 
 /// Expand environment variables in a string.
@@ -31,10 +46,57 @@ pub struct CliConfig {
     pub settlement: SettlementConfig,
     /// Economics configuration.
     pub economics: EconomicsConfig,
+    /// Incoming announcement filtering.
+    pub announcements: AnnouncementsConfig,
+    /// Usage reports sent to and accepted from other peers.
+    pub usage_reports: UsageReportsConfig,
+    /// Moderation policy for content reports.
+    pub moderation: ModerationPolicyConfig,
+    /// Trust policy for content fetched from other peers.
+    pub trust: TrustConfig,
+    /// Proof-of-possession challenges for paid queries we serve.
+    pub query_challenge: QueryChallengesConfig,
+    /// Publisher bonds required of content we pay for.
+    pub bonds: BondsConfig,
+    /// Fraud proofs against providers serving the wrong content.
+    pub fraud_proofs: FraudProofsConfig,
+    /// Data retention policy.
+    pub retention: RetentionPolicyConfig,
+    /// Clock skew detection and correction.
+    pub clock: ClockConfig,
+    /// Peer latency probes and QoS-ranked providers.
+    pub qos: QosSection,
+    /// Content availability health checks.
+    pub availability: AvailabilitySection,
+    /// State snapshots for fast sync.
+    pub snapshot: SnapshotSection,
+    /// Our storefront in the DHT.
+    pub storefront: StorefrontSection,
+    /// Content popularity and cache prewarming.
+    pub popularity: PopularitySection,
+    /// Standing orders buying announced content.
+    pub standing_orders: StandingOrdersSection,
+    /// Re-downloads of content bought from us.
+    pub entitlements: EntitlementsSection,
+    /// Watching and countering channel disputes.
+    pub disputes: DisputesSection,
+    /// RSS/Atom and ActivityPub bridge.
+    pub bridge: BridgeConfig,
+    /// IPFS imports.
+    pub ipfs: IpfsConfig,
     /// Display configuration.
     pub display: DisplayConfig,
     /// Alerting configuration.
     pub alerting: AlertingConfig,
+    /// Log file configuration.
+    pub logging: LoggingConfig,
+    /// Earnings and activity digests.
+    pub notifications: NotificationsConfig,
+    /// Advanced operations settings, applied over the sections above.
+    ///
+    /// Tables mirror `OpsConfig` (e.g. `[ops.search]`, `[ops.close_batch]`).
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    pub ops: toml::Table,
 }
 
 impl Default for CliConfig {
@@ -46,15 +108,38 @@ impl Default for CliConfig {
             network: NetworkConfigSection::default(),
             settlement: SettlementConfig::default(),
             economics: EconomicsConfig::default(),
+            announcements: AnnouncementsConfig::default(),
+            usage_reports: UsageReportsConfig::default(),
+            moderation: ModerationPolicyConfig::default(),
+            trust: TrustConfig::default(),
+            query_challenge: QueryChallengesConfig::default(),
+            bonds: BondsConfig::default(),
+            fraud_proofs: FraudProofsConfig::default(),
+            retention: RetentionPolicyConfig::default(),
+            clock: ClockConfig::default(),
+            qos: QosSection::default(),
+            availability: AvailabilitySection::default(),
+            snapshot: SnapshotSection::default(),
+            storefront: StorefrontSection::default(),
+            popularity: PopularitySection::default(),
+            standing_orders: StandingOrdersSection::default(),
+            entitlements: EntitlementsSection::default(),
+            disputes: DisputesSection::default(),
+            bridge: BridgeConfig::default(),
+            ipfs: IpfsConfig::default(),
             display: DisplayConfig::default(),
             alerting: AlertingConfig::default(),
+            logging: LoggingConfig::default(),
+            notifications: NotificationsConfig::default(),
+            ops: toml::Table::new(),
         }
     }
 }
 
 impl CliConfig {
     /// Load configuration from a file.
-    /// Environment variables in `${VAR}` format are expanded in webhook URLs.
+    /// Environment variables in `${VAR}` format are expanded in webhook URLs
+    /// and the SMTP password.
     pub fn load(path: &Path) -> CliResult<Self> {
         if !path.exists() {
             return Ok(Self::default());
@@ -66,6 +151,12 @@ impl CliConfig {
         for webhook in &mut config.alerting.webhooks {
             webhook.url = expand_env_vars(&webhook.url);
         }
+        for webhook in &mut config.notifications.webhooks {
+            webhook.url = expand_env_vars(&webhook.url);
+        }
+        if let Some(email) = &mut config.notifications.email {
+            email.password = email.password.as_deref().map(expand_env_vars);
+        }
 
         Ok(config)
     }
@@ -97,6 +188,49 @@ impl CliConfig {
     pub fn base_dir(&self) -> PathBuf {
         self.storage.base_dir()
     }
+
+    /// Build the validated operations configuration.
+    ///
+    /// The CLI sections are converted first, then `[ops]` overrides apply.
+    pub fn ops_config(&self) -> CliResult<OpsConfig> {
+        let settlement = &self.settlement;
+        let config = OpsConfig::default()
+            .with_channel(
+                ChannelConfig::default()
+                    .with_max_accept_deposit(hbar_to_tinybars(settlement.max_accept_deposit_hbar))
+                    .with_auto_deposit(settlement.auto_deposit)
+                    .with_auto_deposit_amount(hbar_to_tinybars(settlement.auto_deposit_amount_hbar))
+                    .with_auto_deposit_min_balance(hbar_to_tinybars(
+                        settlement.min_contract_balance_hbar,
+                    )),
+            )
+            .with_rebalance(
+                RebalanceConfig::default()
+                    .with_auto_rebalance(settlement.auto_rebalance)
+                    .with_skew_threshold(settlement.rebalance_skew_threshold),
+            )
+            .with_announcement_filter(self.announcements.filter_config())
+            .with_announcement_ingest(self.announcements.ingest_config())
+            .with_top_up(settlement.top_up_config())
+            .with_usage_reports(self.usage_reports.ops_config())
+            .with_moderation(self.moderation.ops_config())
+            .with_query_challenge(self.query_challenge.ops_config())
+            .with_bonds(self.bonds.ops_config())
+            .with_fraud_proofs(self.fraud_proofs.ops_config())
+            .with_retention(self.retention.ops_config())
+            .with_clock(self.clock.ops_config())
+            .with_qos(self.qos.ops_config())
+            .with_availability(self.availability.ops_config()?)
+            .with_snapshot(self.snapshot.ops_config())
+            .with_storefront(self.storefront.ops_config()?)
+            .with_popularity(self.popularity.ops_config())
+            .with_standing_orders(self.standing_orders.ops_config(&self.base_dir()))
+            .with_entitlements(self.entitlements.ops_config())
+            .with_disputes(self.disputes.ops_config())
+            .with_notifications(self.notifications.ops_config())
+            .with_trust_policy(self.trust.ops_policy()?);
+        Ok(config.merge_toml(self.ops.clone())?)
+    }
 }
 
 /// Identity configuration.
@@ -132,6 +266,16 @@ pub struct StorageConfig {
     /// Maximum cache size in megabytes.
     #[serde(default = "default_cache_max_size")]
     pub cache_max_size_mb: u64,
+    /// Hours between content integrity scrubs (0 disables them).
+    #[serde(default = "default_scrub_interval_hours")]
+    pub scrub_interval_hours: u64,
+    /// Memory for the in-memory manifest index, in megabytes (0 disables it).
+    #[serde(default = "default_manifest_index_mb")]
+    pub manifest_index_mb: u64,
+    /// Postgres URL of a database sharing manifests, peers, channels and the
+    /// settlement queue with other nodes (needs the `postgres` feature).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database_url: Option<String>,
 }
 
 impl StorageConfig {
@@ -141,6 +285,9 @@ impl StorageConfig {
             database: base_dir.join("nodalync.db"),
             cache_dir: base_dir.join("cache"),
             cache_max_size_mb: default_cache_max_size(),
+            scrub_interval_hours: default_scrub_interval_hours(),
+            manifest_index_mb: default_manifest_index_mb(),
+            database_url: None,
         }
     }
 
@@ -163,6 +310,14 @@ fn default_cache_max_size() -> u64 {
     1000
 }
 
+fn default_scrub_interval_hours() -> u64 {
+    24
+}
+
+fn default_manifest_index_mb() -> u64 {
+    16
+}
+
 /// Network configuration section in CLI config.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -173,129 +328,1401 @@ pub struct NetworkConfigSection {
     pub listen_addresses: Vec<String>,
     /// Bootstrap nodes to connect to.
     pub bootstrap_nodes: Vec<String>,
+    /// Domain whose dnsaddr TXT records (`_dnsaddr.<domain>`) list the
+    /// bootstrap nodes. Nodes found there replace `bootstrap_nodes`, which
+    /// remain the fallback.
+    pub dns_bootstrap: Option<String>,
+    /// How long nodes resolved from DNS are reused before resolving again
+    /// (hours); the cache is also used when DNS is unreachable.
+    #[serde(default = "default_dns_bootstrap_cache_hours")]
+    pub dns_bootstrap_cache_hours: u64,
     /// Time to wait for GossipSub propagation (seconds).
     #[serde(default = "default_gossipsub_propagation_wait")]
     pub gossipsub_propagation_wait: u64,
+    /// Run as a bootstrap/relay node: no payments or content serving,
+    /// per-IP rate limits, and DHT records persisted across restarts.
+    pub bootstrap_mode: bool,
+    /// Routing our queries through relays, and relaying others' queries.
+    pub relay: RelayConfigSection,
+    /// Record every wire message sent and received to `replay.log` in the
+    /// data directory, for `nodalync replay`.
+    pub replay_log: bool,
+    /// Size cap of the replay log (MB); the oldest entries are dropped
+    /// past it.
+    #[serde(default = "default_replay_log_max_mb")]
+    pub replay_log_max_mb: u64,
+    /// Capture decoded messages sent and received, per peer, to
+    /// `capture.ndlcap` in the data directory, for `nodalync net inspect`.
+    pub capture: bool,
+}
+
+fn default_gossipsub_propagation_wait() -> u64 {
+    5
+}
+
+fn default_replay_log_max_mb() -> u64 {
+    16
+}
+
+fn default_dns_bootstrap_cache_hours() -> u64 {
+    24
+}
+
+/// Default bootstrap node addresses (US, EU, Asia).
+const DEFAULT_BOOTSTRAP_NODES: &[&str] = &[
+    "/dns4/nodalync-bootstrap.eastus.azurecontainer.io/tcp/9000/p2p/12D3KooWMqrUmZm4e1BJTRMWqKHCe1TSX9Vu83uJLEyCGr2dUjYm",
+    "/dns4/nodalync-eu.northeurope.azurecontainer.io/tcp/9000/p2p/12D3KooWQiK8uHf877wena9MAPHHprXkmGRhAmXAYakRsMfdnk7P",
+    "/dns4/nodalync-asia.southeastasia.azurecontainer.io/tcp/9000/p2p/12D3KooWFojioE6LXFs3qqBdKQeCFuMr2obsMrvXGY69jmhheLfk",
+];
+
+impl Default for NetworkConfigSection {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            listen_addresses: vec!["/ip4/0.0.0.0/tcp/9000".to_string()],
+            bootstrap_nodes: DEFAULT_BOOTSTRAP_NODES
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            dns_bootstrap: None,
+            dns_bootstrap_cache_hours: default_dns_bootstrap_cache_hours(),
+            gossipsub_propagation_wait: default_gossipsub_propagation_wait(),
+            bootstrap_mode: false,
+            relay: RelayConfigSection::default(),
+            replay_log: false,
+            replay_log_max_mb: default_replay_log_max_mb(),
+            capture: false,
+        }
+    }
+}
+
+impl NetworkConfigSection {
+    /// Build the DNS bootstrap configuration, caching resolved nodes in
+    /// `cache_path`; `None` unless a domain is set.
+    pub fn dns_bootstrap_config(&self, cache_path: &Path) -> Option<DnsBootstrapConfig> {
+        let domain = self.dns_bootstrap.as_deref()?.trim().trim_end_matches('.');
+        (!domain.is_empty()).then(|| {
+            DnsBootstrapConfig::new(domain)
+                .with_cache(cache_path)
+                .with_cache_ttl(Duration::from_secs(
+                    self.dns_bootstrap_cache_hours.saturating_mul(3600),
+                ))
+        })
+    }
+}
+
+/// Relayed query configuration, under `[network.relay]`.
+///
+/// Queries for content we don't hold go through the relays in `route`
+/// (entry first, at most two), so the provider never sees who asked. We
+/// need an open payment channel with the first relay.
+///
+/// ```toml
+/// [network.relay]
+/// route = ["ndl1...", "ndl1..."]
+/// route_fee_hbar = 0.01
+/// serve = true
+/// fee_hbar = 0.01
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RelayConfigSection {
+    /// Peer IDs of the relays to route our queries through, entry first.
+    pub route: Vec<String>,
+    /// Fee paid to each relay on the route per query (HBAR).
+    pub route_fee_hbar: f64,
+    /// Relay other nodes' queries.
+    pub serve: bool,
+    /// Fee charged per relayed query when serving (HBAR).
+    pub fee_hbar: f64,
+}
+
+impl RelayConfigSection {
+    /// Parse the route and build the network-layer relay configuration.
+    pub fn net_config(&self) -> CliResult<RelayConfig> {
+        if self.route.len() > MAX_RELAY_HOPS {
+            return Err(CliError::config(format!(
+                "Relay route has {} hops, max is {}",
+                self.route.len(),
+                MAX_RELAY_HOPS
+            )));
+        }
+        let route = self
+            .route
+            .iter()
+            .map(|peer| {
+                peer_id_from_string(peer)
+                    .map_err(|e| CliError::config(format!("Invalid relay {}: {}", peer, e)))
+            })
+            .collect::<CliResult<Vec<_>>>()?;
+
+        let mut config =
+            RelayConfig::default().with_route(route, hbar_to_tinybars(self.route_fee_hbar));
+        if self.serve {
+            config = config.with_serve(hbar_to_tinybars(self.fee_hbar));
+        }
+        Ok(config)
+    }
+}
+
+/// Settlement configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettlementConfig {
+    /// Network to use (e.g., "hedera-testnet", "hedera-mainnet", "mock").
+    pub network: String,
+    /// Account ID for Hedera.
+    pub account_id: Option<String>,
+    /// Path to Hedera private key.
+    pub key_path: Option<PathBuf>,
+    /// Settlement contract ID for Hedera.
+    pub contract_id: Option<String>,
+    /// Enable auto-deposit on startup and when accepting channels.
+    /// When enabled, the node will automatically deposit HBAR to the settlement
+    /// contract to ensure it can accept payment channels from other peers.
+    /// Default: false (opt-in for security).
+    #[serde(default = "default_auto_deposit")]
+    pub auto_deposit: bool,
+    /// Minimum balance to maintain in the settlement contract (in HBAR).
+    /// If the balance falls below this, auto-deposit will trigger.
+    #[serde(default = "default_min_contract_balance")]
+    pub min_contract_balance_hbar: f64,
+    /// Amount to deposit when auto-deposit triggers (in HBAR).
+    #[serde(default = "default_auto_deposit_amount")]
+    pub auto_deposit_amount_hbar: f64,
+    /// Maximum deposit to accept/match when a peer opens a channel (in HBAR).
+    /// Caps how much you'll commit when accepting a channel request.
+    /// This is a security measure to prevent unbounded commitment.
+    #[serde(default = "default_max_accept_deposit")]
+    pub max_accept_deposit_hbar: f64,
+    /// Automatically rebalance skewed channels on the settlement interval.
+    /// Rebalancing closes and reopens depleted channels, which costs fees.
+    /// Default: false (opt-in).
+    #[serde(default)]
+    pub auto_rebalance: bool,
+    /// Share of a channel's capacity on one side above which the channel
+    /// counts as skewed (0.5 - 1.0).
+    #[serde(default = "default_rebalance_skew_threshold")]
+    pub rebalance_skew_threshold: f64,
+    /// Top up the settlement balance automatically while the node or MCP
+    /// server runs, so queries don't fail when it runs dry.
+    /// Default: false (opt-in).
+    #[serde(default)]
+    pub auto_top_up: bool,
+    /// Balance below which a top-up is made (in HBAR).
+    #[serde(default = "default_top_up_threshold")]
+    pub top_up_threshold_hbar: f64,
+    /// Amount deposited per top-up (in HBAR).
+    #[serde(default = "default_top_up_amount")]
+    pub top_up_amount_hbar: f64,
+    /// Maximum total of top-ups in any 24 hours (in HBAR).
+    #[serde(default = "default_top_up_daily_cap")]
+    pub top_up_daily_cap_hbar: f64,
+    /// How often the settlement balance is checked (in seconds).
+    #[serde(default = "default_top_up_check_interval")]
+    pub top_up_check_interval_secs: u64,
+}
+
+fn default_auto_deposit() -> bool {
+    // SECURITY: Default to false (opt-in) to prevent automatic deposits
+    // without explicit user consent. Users must explicitly enable this.
+    false
+}
+
+fn default_min_contract_balance() -> f64 {
+    100.0 // 100 HBAR minimum
+}
+
+fn default_auto_deposit_amount() -> f64 {
+    200.0 // Deposit 200 HBAR when triggered
+}
+
+fn default_max_accept_deposit() -> f64 {
+    500.0 // 500 HBAR max accept deposit per channel
+}
+
+fn default_rebalance_skew_threshold() -> f64 {
+    0.8
+}
+
+fn default_top_up_threshold() -> f64 {
+    10.0 // Top up below 10 HBAR
+}
+
+fn default_top_up_amount() -> f64 {
+    50.0 // Deposit 50 HBAR per top-up
+}
+
+fn default_top_up_daily_cap() -> f64 {
+    200.0 // At most 200 HBAR per day
+}
+
+fn default_top_up_check_interval() -> u64 {
+    60
+}
+
+impl Default for SettlementConfig {
+    fn default() -> Self {
+        Self {
+            network: "hedera-testnet".to_string(),
+            account_id: None,
+            key_path: None,
+            contract_id: None,
+            auto_deposit: default_auto_deposit(),
+            min_contract_balance_hbar: default_min_contract_balance(),
+            auto_deposit_amount_hbar: default_auto_deposit_amount(),
+            max_accept_deposit_hbar: default_max_accept_deposit(),
+            auto_rebalance: false,
+            rebalance_skew_threshold: default_rebalance_skew_threshold(),
+            auto_top_up: false,
+            top_up_threshold_hbar: default_top_up_threshold(),
+            top_up_amount_hbar: default_top_up_amount(),
+            top_up_daily_cap_hbar: default_top_up_daily_cap(),
+            top_up_check_interval_secs: default_top_up_check_interval(),
+        }
+    }
+}
+
+impl SettlementConfig {
+    /// Build the ops-layer settlement balance top-up configuration.
+    pub fn top_up_config(&self) -> TopUpConfig {
+        TopUpConfig::default()
+            .with_enabled(self.auto_top_up)
+            .with_min_balance(hbar_to_tinybars(self.top_up_threshold_hbar))
+            .with_amount(hbar_to_tinybars(self.top_up_amount_hbar))
+            .with_daily_cap(hbar_to_tinybars(self.top_up_daily_cap_hbar))
+            .with_check_interval(self.top_up_check_interval_secs)
+    }
+}
+
+/// Economics configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EconomicsConfig {
+    /// Default price for published content (in HBAR).
+    pub default_price: f64,
+    /// Threshold for automatic settlement (in HBAR).
+    pub auto_settle_threshold: f64,
+}
+
+impl Default for EconomicsConfig {
+    fn default() -> Self {
+        Self {
+            default_price: 0.10,
+            auto_settle_threshold: 100.0,
+        }
+    }
+}
+
+impl EconomicsConfig {
+    /// Convert HBAR to tinybars (10^-8 HBAR).
+    pub fn default_price_units(&self) -> u64 {
+        hbar_to_tinybars(self.default_price)
+    }
+
+    /// Convert HBAR to tinybars (10^-8 HBAR).
+    pub fn auto_settle_threshold_units(&self) -> u64 {
+        ndl_to_units(self.auto_settle_threshold)
+    }
+}
+
+/// Incoming announcement filter and ingestion configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnnouncementsConfig {
+    /// Minimum publisher reputation (unset accepts any publisher).
+    pub min_reputation: Option<i64>,
+    /// Lowest acceptable price (in HBAR).
+    pub min_price: f64,
+    /// Highest acceptable price (in HBAR, unset uses the protocol maximum).
+    pub max_price: Option<f64>,
+    /// Drop announcements whose titles contain any of these.
+    pub title_blocklist: Vec<String>,
+    /// Drop announcements whose titles match any of these regular expressions.
+    pub title_patterns: Vec<String>,
+    /// Maximum announcements accepted from one peer per minute (0 = unlimited).
+    pub max_per_peer_per_minute: u32,
+    /// Keep only a publisher's latest announcement for each title.
+    pub collapse_duplicate_titles: bool,
+    /// Most announcements a running node stores per batch.
+    pub ingest_batch_size: usize,
+    /// Longest an announcement waits for its batch, in milliseconds.
+    pub ingest_flush_interval_ms: u64,
+    /// Most announcements waiting to be stored before new ones are shed.
+    pub ingest_max_pending: usize,
+}
+
+impl Default for AnnouncementsConfig {
+    fn default() -> Self {
+        let ingest = AnnouncementIngestConfig::default();
+        Self {
+            min_reputation: None,
+            min_price: 0.0,
+            max_price: None,
+            title_blocklist: Vec::new(),
+            title_patterns: Vec::new(),
+            max_per_peer_per_minute: 60,
+            collapse_duplicate_titles: true,
+            ingest_batch_size: ingest.batch_size,
+            ingest_flush_interval_ms: ingest.flush_interval_ms,
+            ingest_max_pending: ingest.max_pending,
+        }
+    }
+}
+
+impl AnnouncementsConfig {
+    /// Build the ops-layer announcement filter configuration.
+    pub fn filter_config(&self) -> AnnouncementFilterConfig {
+        let max_price = self
+            .max_price
+            .map(hbar_to_tinybars)
+            .unwrap_or(nodalync_types::MAX_PRICE);
+        let mut filter = AnnouncementFilterConfig::default()
+            .with_price_range(hbar_to_tinybars(self.min_price), max_price)
+            .with_title_blocklist(self.title_blocklist.clone())
+            .with_title_patterns(self.title_patterns.clone())
+            .with_max_per_peer_per_minute(Some(self.max_per_peer_per_minute).filter(|max| *max > 0))
+            .with_collapse_duplicate_titles(self.collapse_duplicate_titles);
+        if let Some(reputation) = self.min_reputation {
+            filter = filter.with_min_reputation(reputation);
+        }
+        filter
+    }
+
+    /// Build the ops-layer announcement ingestion configuration.
+    pub fn ingest_config(&self) -> AnnouncementIngestConfig {
+        AnnouncementIngestConfig::default()
+            .with_batch_size(self.ingest_batch_size)
+            .with_flush_interval(self.ingest_flush_interval_ms)
+            .with_max_pending(self.ingest_max_pending)
+    }
+}
+
+/// RSS/Atom and ActivityPub bridge.
+///
+/// With `enabled`, a running node serves its Shared content as RSS and Atom
+/// feeds and as an ActivityPub actor on `listen`, linking each item to a
+/// preview page under `public_url`. Items of the external `feeds` are
+/// polled every `poll_interval_secs` and stored as unpaid, preview-only
+/// announcements.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BridgeConfig {
+    /// Whether the bridge runs with the node.
+    pub enabled: bool,
+    /// Address the bridge listens on.
+    pub listen: String,
+    /// Public URL the bridge is reachable at (e.g. `https://node.example.com`).
+    ///
+    /// Feed, actor and preview links are built from it. Unset uses
+    /// `http://<listen>`, which is only reachable locally.
+    pub public_url: Option<String>,
+    /// ActivityPub username, as in `@<actor_name>@<host>`.
+    pub actor_name: String,
+    /// Feed title (defaults to the node's peer ID).
+    pub title: Option<String>,
+    /// Most items in our feeds and outbox.
+    pub max_items: u32,
+    /// External RSS or Atom feeds to ingest.
+    pub feeds: Vec<String>,
+    /// How often external feeds are polled, in seconds.
+    pub poll_interval_secs: u64,
+}
+
+impl Default for BridgeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: "127.0.0.1:8090".to_string(),
+            public_url: None,
+            actor_name: "nodalync".to_string(),
+            title: None,
+            max_items: 50,
+            feeds: Vec::new(),
+            poll_interval_secs: 3600,
+        }
+    }
+}
+
+impl BridgeConfig {
+    /// The public base URL, without a trailing slash.
+    pub fn base_url(&self) -> String {
+        self.public_url
+            .clone()
+            .unwrap_or_else(|| format!("http://{}", self.listen))
+            .trim_end_matches('/')
+            .to_string()
+    }
+}
+
+/// IPFS import configuration.
+///
+/// `nodalync import ipfs` fetches through the node `api` when one is set,
+/// which verifies every block, and through `gateway` otherwise. Pinning a
+/// record back to IPFS needs the node `api`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IpfsConfig {
+    /// HTTP gateway to fetch CIDs from.
+    pub gateway: String,
+    /// IPFS node RPC API (e.g. `http://127.0.0.1:5001`).
+    pub api: Option<String>,
+    /// Largest content imported, in megabytes.
+    pub max_import_mb: u64,
+    /// Timeout for one IPFS request, in seconds.
+    pub timeout_secs: u64,
+}
+
+impl Default for IpfsConfig {
+    fn default() -> Self {
+        Self {
+            gateway: "https://ipfs.io".to_string(),
+            api: None,
+            max_import_mb: 100,
+            timeout_secs: 120,
+        }
+    }
+}
+
+/// Earnings and activity digests.
+///
+/// With `daily` or `weekly` set, a running node sends a digest of each
+/// period once it ends: earnings, the top earning content, new consumers
+/// and failed settlements. Digests go to every notifier configured here,
+/// by email through `email` and as a post to each of `webhooks` (the
+/// `generic` type posts the digest as JSON, `slack` and `discord` post its
+/// text; `alert_types` doesn't apply).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Send a digest for each UTC day.
+    pub daily: bool,
+    /// Send a digest for each week, starting Monday.
+    pub weekly: bool,
+    /// Most earning content listed in a digest.
+    pub top_content: usize,
+    /// Email delivery over SMTP.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<EmailConfig>,
+    /// Webhooks the digest is posted to.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookConfig>,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        let defaults = NotificationConfig::default();
+        Self {
+            daily: defaults.daily,
+            weekly: defaults.weekly,
+            top_content: defaults.top_content,
+            email: None,
+            webhooks: Vec::new(),
+        }
+    }
+}
+
+impl NotificationsConfig {
+    /// Build the ops-layer digest configuration.
+    pub fn ops_config(&self) -> NotificationConfig {
+        NotificationConfig::default()
+            .with_period(DigestPeriod::Daily, self.daily)
+            .with_period(DigestPeriod::Weekly, self.weekly)
+            .with_top_content(self.top_content)
+    }
+}
+
+/// SMTP settings for emailed digests.
+///
+/// The connection is upgraded with STARTTLS unless `tls` is `"none"`, and
+/// uses implicit TLS with `"wrapper"` (usually port 465).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailConfig {
+    /// SMTP server host.
+    pub smtp_host: String,
+    /// SMTP server port.
+    #[serde(default = "default_smtp_port")]
+    pub smtp_port: u16,
+    /// TLS mode: `starttls`, `wrapper` or `none`.
+    #[serde(default)]
+    pub tls: SmtpTls,
+    /// SMTP username, if the server requires login.
+    pub username: Option<String>,
+    /// SMTP password; `${VAR}` is expanded from the environment.
+    pub password: Option<String>,
+    /// Sender address.
+    pub from: String,
+    /// Recipient addresses.
+    pub to: Vec<String>,
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+/// How the SMTP connection is secured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Upgrade a plain connection with STARTTLS.
+    #[default]
+    Starttls,
+    /// Connect over TLS from the start.
+    Wrapper,
+    /// No TLS (local relays only).
+    None,
+}
+
+/// Usage report (read receipt) configuration.
+///
+/// Both are opt-in: reports are only sent with `send` and only recorded for
+/// our content with `accept`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageReportsConfig {
+    /// Send usage reports for content we queried.
+    pub send: bool,
+    /// Record usage reports for our content.
+    pub accept: bool,
+    /// Maximum reports accepted from one peer per hour.
+    pub max_per_peer_per_hour: u32,
+}
+
+impl Default for UsageReportsConfig {
+    fn default() -> Self {
+        let defaults = UsageReportConfig::default();
+        Self {
+            send: defaults.send,
+            accept: defaults.accept,
+            max_per_peer_per_hour: defaults.max_per_peer_per_hour,
+        }
+    }
+}
+
+impl UsageReportsConfig {
+    /// Build the ops-layer usage report configuration.
+    pub fn ops_config(&self) -> UsageReportConfig {
+        UsageReportConfig::default()
+            .with_send(self.send)
+            .with_accept(self.accept)
+            .with_max_per_peer_per_hour(self.max_per_peer_per_hour)
+    }
+}
+
+/// Proof-of-possession challenges for paid queries we serve.
+///
+/// Paid queries for content priced at `min_price` or more must answer a
+/// signed challenge first. Disabled unless `min_price` is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueryChallengesConfig {
+    /// Lowest content price that is challenged (in HBAR).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_price: Option<f64>,
+    /// Seconds a challenge can be answered in.
+    pub ttl_secs: u64,
+}
+
+impl Default for QueryChallengesConfig {
+    fn default() -> Self {
+        Self {
+            min_price: None,
+            ttl_secs: QueryChallengeConfig::default().ttl_ms / 1000,
+        }
+    }
+}
+
+impl QueryChallengesConfig {
+    /// Build the ops-layer query challenge configuration.
+    pub fn ops_config(&self) -> QueryChallengeConfig {
+        let mut config =
+            QueryChallengeConfig::default().with_ttl_ms(self.ttl_secs.saturating_mul(1000));
+        if let Some(min_price) = self.min_price {
+            config = config.with_min_price(hbar_to_tinybars(min_price));
+        }
+        config
+    }
+}
+
+/// Publisher bonds required of content we pay for.
+///
+/// Content without a large enough bond for its visibility is refused
+/// before payment. No bonds are required by default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BondsConfig {
+    /// Minimum bond for unlisted content (in HBAR).
+    pub unlisted: f64,
+    /// Minimum bond for shared content (in HBAR).
+    pub shared: f64,
+    /// Slash the bond of a publisher caught serving bad content.
+    pub slash_on_fraud: bool,
+    /// Seconds a publisher's on-chain bond is cached.
+    pub cache_ttl_secs: u64,
+}
+
+impl Default for BondsConfig {
+    fn default() -> Self {
+        Self {
+            unlisted: 0.0,
+            shared: 0.0,
+            slash_on_fraud: false,
+            cache_ttl_secs: BondConfig::default().cache_ttl_ms / 1000,
+        }
+    }
+}
+
+impl BondsConfig {
+    /// Build the ops-layer bond configuration.
+    pub fn ops_config(&self) -> BondConfig {
+        BondConfig::default()
+            .with_requirements(
+                BondRequirements::new()
+                    .with_unlisted(hbar_to_tinybars(self.unlisted))
+                    .with_shared(hbar_to_tinybars(self.shared)),
+            )
+            .with_slash_on_fraud(self.slash_on_fraud)
+            .with_cache_ttl_ms(self.cache_ttl_secs.saturating_mul(1000))
+    }
+}
+
+/// Fraud proofs against providers serving the wrong content.
+///
+/// Proofs we make are always applied and broadcast; these settings cover
+/// proofs from other peers and recording ours on-chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FraudProofsConfig {
+    /// Act on fraud proofs broadcast by other peers.
+    pub accept_proofs: bool,
+    /// Reputation a provider loses for each proof against it.
+    pub reputation_penalty: i64,
+    /// Record proofs we make on-chain.
+    pub submit_on_chain: bool,
+}
+
+impl Default for FraudProofsConfig {
+    fn default() -> Self {
+        let defaults = FraudProofConfig::default();
+        Self {
+            accept_proofs: defaults.accept_proofs,
+            reputation_penalty: defaults.reputation_penalty,
+            submit_on_chain: defaults.submit_on_chain,
+        }
+    }
+}
+
+impl FraudProofsConfig {
+    /// Build the ops-layer fraud proof configuration.
+    pub fn ops_config(&self) -> FraudProofConfig {
+        FraudProofConfig::default()
+            .with_accept_proofs(self.accept_proofs)
+            .with_reputation_penalty(self.reputation_penalty)
+            .with_submit_on_chain(self.submit_on_chain)
+    }
+}
+
+/// Data retention policy, in days per category.
+///
+/// A running node deletes records older than their category's limit every
+/// `check_interval_secs`; unset categories are kept forever. Peers and
+/// channels still in use are always kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicyConfig {
+    /// Days to keep announcements received from the network.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub announcements_days: Option<u64>,
+    /// Days to keep content cached from queries.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cached_content_days: Option<u64>,
+    /// Days to keep peers not seen since.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer_records_days: Option<u64>,
+    /// Days to keep closed channels and settled payments.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_history_days: Option<u64>,
+    /// Days to keep access logs and usage reports.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub analytics_days: Option<u64>,
+    /// How often the policy is enforced, in seconds.
+    pub check_interval_secs: u64,
+}
+
+impl Default for RetentionPolicyConfig {
+    fn default() -> Self {
+        Self {
+            announcements_days: None,
+            cached_content_days: None,
+            peer_records_days: None,
+            channel_history_days: None,
+            analytics_days: None,
+            check_interval_secs: RetentionConfig::default().check_interval_secs,
+        }
+    }
+}
+
+impl RetentionPolicyConfig {
+    /// Build the ops-layer retention configuration.
+    pub fn ops_config(&self) -> RetentionConfig {
+        let days = |d: Option<u64>| d.map(|d| d.saturating_mul(24 * 60 * 60));
+        RetentionConfig::default()
+            .with_ttl(
+                RetentionCategory::Announcements,
+                days(self.announcements_days),
+            )
+            .with_ttl(
+                RetentionCategory::CachedContent,
+                days(self.cached_content_days),
+            )
+            .with_ttl(RetentionCategory::PeerRecords, days(self.peer_records_days))
+            .with_ttl(
+                RetentionCategory::ChannelHistory,
+                days(self.channel_history_days),
+            )
+            .with_ttl(RetentionCategory::Analytics, days(self.analytics_days))
+            .with_check_interval(self.check_interval_secs)
+    }
+}
+
+/// Clock skew detection and correction.
+///
+/// A running node pings connected peers every `probe_interval_secs` and
+/// estimates how far its clock is from theirs. With `correct`, message
+/// timestamps are adjusted by the estimate; an estimate beyond
+/// `tolerance_secs` is logged as a warning either way.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClockConfig {
+    /// Whether to correct message timestamps by the estimated offset.
+    pub correct: bool,
+    /// Offset from the network beyond which to warn, in seconds.
+    pub tolerance_secs: u64,
+    /// Minimum number of peers that must answer for an estimate.
+    pub min_peers: usize,
+    /// Maximum number of peers pinged per probe.
+    pub max_peers: usize,
+    /// How often peers are probed, in seconds.
+    pub probe_interval_secs: u64,
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        let defaults = ClockSkewConfig::default();
+        Self {
+            correct: defaults.correct,
+            tolerance_secs: defaults.tolerance_ms / 1000,
+            min_peers: defaults.min_peers,
+            max_peers: defaults.max_peers,
+            probe_interval_secs: defaults.probe_interval_secs,
+        }
+    }
+}
+
+impl ClockConfig {
+    /// Build the ops-layer clock skew configuration.
+    pub fn ops_config(&self) -> ClockSkewConfig {
+        ClockSkewConfig::default()
+            .with_correct(self.correct)
+            .with_tolerance_ms(self.tolerance_secs.saturating_mul(1000))
+            .with_peers(self.min_peers, self.max_peers)
+            .with_probe_interval(self.probe_interval_secs)
+    }
+}
+
+/// Peer latency probes and QoS-ranked providers.
+///
+/// With `enabled`, a running node pings known peers every
+/// `probe_interval_secs` and keeps their latency, jitter and loss in the
+/// peer store (`nodalync peers list`). Content providers are tried best
+/// first by reputation blended with their QoS score, `weight` being the
+/// QoS share.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QosSection {
+    /// Whether to probe peers periodically.
+    pub enabled: bool,
+    /// Maximum number of peers pinged per probe.
+    pub max_peers: usize,
+    /// How often peers are probed, in seconds.
+    pub probe_interval_secs: u64,
+    /// QoS share of a provider's rank, from 0.0 to 1.0.
+    pub weight: f64,
+}
+
+impl Default for QosSection {
+    fn default() -> Self {
+        let defaults = QosConfig::default();
+        Self {
+            enabled: defaults.enabled,
+            max_peers: defaults.max_peers,
+            probe_interval_secs: defaults.probe_interval_secs,
+            weight: defaults.weight,
+        }
+    }
+}
+
+impl QosSection {
+    /// Build the ops-layer QoS configuration.
+    pub fn ops_config(&self) -> QosConfig {
+        QosConfig::default()
+            .with_enabled(self.enabled)
+            .with_max_peers(self.max_peers)
+            .with_probe_interval(self.probe_interval_secs)
+            .with_weight(self.weight)
+    }
+}
+
+/// Content availability health checks.
+///
+/// With `enabled`, a running node asks a remote peer every
+/// `check_interval_secs` to dial it and preview up to `sample_size` of its
+/// shared content, and records whether each was reachable
+/// (`nodalync availability`). `checkers` are peers to ask first, such as a
+/// checker service; otherwise any connected peer is asked. Content failing
+/// `alert_after` checks in a row triggers the `content_unreachable`
+/// webhook.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AvailabilitySection {
+    /// Whether to check our content periodically.
+    pub enabled: bool,
+    /// How often content is checked, in seconds.
+    pub check_interval_secs: u64,
+    /// Most content checked per run.
+    pub sample_size: usize,
+    /// Peer IDs to ask first.
+    pub checkers: Vec<String>,
+    /// Consecutive failures before content is reported.
+    pub alert_after: u32,
+    /// Whether to check other peers' content when asked.
+    pub serve: bool,
+}
+
+impl Default for AvailabilitySection {
+    fn default() -> Self {
+        let defaults = AvailabilityConfig::default();
+        Self {
+            enabled: defaults.enabled,
+            check_interval_secs: defaults.check_interval_secs,
+            sample_size: defaults.sample_size,
+            checkers: Vec::new(),
+            alert_after: defaults.alert_after,
+            serve: defaults.serve,
+        }
+    }
+}
+
+impl AvailabilitySection {
+    /// Parse the checkers and build the ops-layer availability configuration.
+    pub fn ops_config(&self) -> CliResult<AvailabilityConfig> {
+        let checkers = self
+            .checkers
+            .iter()
+            .map(|peer| {
+                peer_id_from_string(peer)
+                    .map_err(|e| CliError::config(format!("Invalid checker {}: {}", peer, e)))
+            })
+            .collect::<CliResult<Vec<_>>>()?;
+        Ok(AvailabilityConfig::default()
+            .with_enabled(self.enabled)
+            .with_check_interval(self.check_interval_secs)
+            .with_sample_size(self.sample_size)
+            .with_checkers(checkers)
+            .with_alert_after(self.alert_after)
+            .with_serve(self.serve))
+    }
+}
+
+/// State snapshots for fast sync.
+///
+/// With `serve`, the node answers snapshot requests with a signed copy of
+/// its announcement index and peer list; leave it off unless the node is
+/// well connected. With `sync_on_start`, a node that knows no content
+/// imports a snapshot from the first connected peer that serves one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotSection {
+    /// Whether to answer snapshot requests.
+    pub serve: bool,
+    /// Whether to fast sync on start when no content is known.
+    pub sync_on_start: bool,
+    /// Oldest snapshot imported, in seconds.
+    pub max_age_secs: u64,
+    /// Peers from an imported snapshot dialed straight away.
+    pub dial_peers: usize,
+}
+
+impl Default for SnapshotSection {
+    fn default() -> Self {
+        let defaults = SnapshotConfig::default();
+        Self {
+            serve: defaults.serve,
+            sync_on_start: true,
+            max_age_secs: defaults.max_age_ms / 1000,
+            dial_peers: defaults.dial_peers,
+        }
+    }
+}
+
+impl SnapshotSection {
+    /// Build the ops-layer snapshot configuration.
+    pub fn ops_config(&self) -> SnapshotConfig {
+        SnapshotConfig::default()
+            .with_serve(self.serve)
+            .with_max_age(self.max_age_secs.saturating_mul(1000))
+            .with_dial_peers(self.dial_peers)
+    }
+}
+
+/// Milliseconds in a day.
+const MS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+/// Re-downloads of content bought from us.
+///
+/// With `enabled`, a requester whose copy of content it bought from us was
+/// evicted can present its receipt to get the content again free, for
+/// `window_days` after buying it (`nodalync library --open`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntitlementsSection {
+    /// Whether to serve re-downloads.
+    pub enabled: bool,
+    /// How long after a purchase it can be re-downloaded, in days.
+    pub window_days: u64,
+}
+
+impl Default for EntitlementsSection {
+    fn default() -> Self {
+        let defaults = EntitlementConfig::default();
+        Self {
+            enabled: defaults.enabled,
+            window_days: defaults.window_ms / MS_PER_DAY,
+        }
+    }
+}
+
+impl EntitlementsSection {
+    /// Build the ops-layer entitlement configuration.
+    pub fn ops_config(&self) -> EntitlementConfig {
+        EntitlementConfig::default()
+            .with_enabled(self.enabled)
+            .with_window_ms(self.window_days.saturating_mul(MS_PER_DAY))
+    }
+}
+
+/// Milliseconds in an hour.
+const MS_PER_HOUR: u64 = 60 * 60 * 1000;
+
+/// Watching and countering channel disputes.
+///
+/// With `enabled`, a running node checks its channels for disputes on-chain
+/// every `check_interval_secs` and warns `warn_before_hours` before the
+/// deadline of one the counterparty opened. With `auto_counter`, a dispute
+/// submitted with an older state than ours is countered with our latest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisputesSection {
+    /// Whether to watch for disputes.
+    pub enabled: bool,
+    /// Whether to counter stale disputed states automatically.
+    pub auto_counter: bool,
+    /// How long before a dispute deadline to warn, in hours.
+    pub warn_before_hours: u64,
+    /// Seconds between dispute checks.
+    pub check_interval_secs: u64,
+}
+
+impl Default for DisputesSection {
+    fn default() -> Self {
+        let defaults = DisputeConfig::default();
+        Self {
+            enabled: defaults.enabled,
+            auto_counter: defaults.auto_counter,
+            warn_before_hours: defaults.warn_before_ms / MS_PER_HOUR,
+            check_interval_secs: defaults.check_interval_secs,
+        }
+    }
+}
+
+impl DisputesSection {
+    /// Build the ops-layer dispute configuration.
+    pub fn ops_config(&self) -> DisputeConfig {
+        DisputeConfig::default()
+            .with_enabled(self.enabled)
+            .with_auto_counter(self.auto_counter)
+            .with_warn_before_ms(self.warn_before_hours.saturating_mul(MS_PER_HOUR))
+            .with_check_interval(self.check_interval_secs)
+    }
+}
+
+/// Our storefront in the DHT.
+///
+/// With `enabled`, a running node signs a storefront listing our shared
+/// content every `refresh_interval_secs` and stores it in the DHT under our
+/// peer ID, for consumers to browse with `nodalync storefront`. `featured`
+/// content hashes are listed first; items are filed under their first tag.
+/// Fetched storefronts older than `max_age_secs` are refused.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorefrontSection {
+    /// Whether to republish our storefront periodically.
+    pub enabled: bool,
+    /// Display name of our storefront.
+    pub name: String,
+    /// Content hashes to feature, in display order.
+    pub featured: Vec<String>,
+    /// How often our storefront is republished, in seconds.
+    pub refresh_interval_secs: u64,
+    /// Oldest storefront accepted when fetching, in seconds.
+    pub max_age_secs: u64,
+}
+
+impl Default for StorefrontSection {
+    fn default() -> Self {
+        let defaults = StorefrontConfig::default();
+        Self {
+            enabled: defaults.enabled,
+            name: defaults.name,
+            featured: Vec::new(),
+            refresh_interval_secs: defaults.refresh_interval_secs,
+            max_age_secs: defaults.max_age_ms / 1000,
+        }
+    }
+}
+
+impl StorefrontSection {
+    /// Parse the featured hashes and build the ops-layer storefront
+    /// configuration.
+    pub fn ops_config(&self) -> CliResult<StorefrontConfig> {
+        let featured = self
+            .featured
+            .iter()
+            .map(|hash| {
+                parse_hash(hash)
+                    .map_err(|_| CliError::config(format!("Invalid featured hash {}", hash)))
+            })
+            .collect::<CliResult<Vec<_>>>()?;
+        Ok(StorefrontConfig::default()
+            .with_enabled(self.enabled)
+            .with_name(self.name.clone())
+            .with_featured(featured)
+            .with_refresh_interval(self.refresh_interval_secs)
+            .with_max_age(self.max_age_secs.saturating_mul(1000)))
+    }
+}
+
+/// Content popularity and cache prewarming.
+///
+/// Requests for each piece of content are counted into a score that halves
+/// every `half_life_hours`. Every `prewarm_interval_secs` a running node
+/// keeps the manifests of the `hot_manifests` most popular items in memory
+/// and holds on to popular cached content; with `fetch`, it also fetches
+/// up to `max_fetch` popular free items it doesn't have.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PopularitySection {
+    /// Whether to count requests.
+    pub enabled: bool,
+    /// Time for a popularity score to halve, in hours.
+    pub half_life_hours: u64,
+    /// Minimum score for content to count as popular.
+    pub min_score: f64,
+    /// Manifests of the most popular content held in memory.
+    pub hot_manifests: usize,
+    /// Whether to fetch popular free content ahead of demand.
+    pub fetch: bool,
+    /// Most content fetched per prewarm run.
+    pub max_fetch: usize,
+    /// How often to prewarm, in seconds.
+    pub prewarm_interval_secs: u64,
+}
+
+impl Default for PopularitySection {
+    fn default() -> Self {
+        let defaults = PopularityConfig::default();
+        Self {
+            enabled: defaults.enabled,
+            half_life_hours: defaults.half_life_ms / 3_600_000,
+            min_score: defaults.min_score,
+            hot_manifests: defaults.hot_manifests,
+            fetch: defaults.fetch,
+            max_fetch: defaults.max_fetch,
+            prewarm_interval_secs: defaults.prewarm_interval_secs,
+        }
+    }
 }
 
-fn default_gossipsub_propagation_wait() -> u64 {
-    5
+impl PopularitySection {
+    /// Build the ops-layer popularity configuration.
+    pub fn ops_config(&self) -> PopularityConfig {
+        let mut config = PopularityConfig::default()
+            .with_enabled(self.enabled)
+            .with_half_life(self.half_life_hours.saturating_mul(3_600_000))
+            .with_min_score(self.min_score)
+            .with_hot_manifests(self.hot_manifests)
+            .with_prewarm_interval(self.prewarm_interval_secs);
+        config.max_fetch = self.max_fetch;
+        if self.fetch {
+            config = config.with_fetch(self.max_fetch);
+        }
+        config
+    }
 }
 
-/// Default bootstrap node addresses (US, EU, Asia).
-const DEFAULT_BOOTSTRAP_NODES: &[&str] = &[
-    "/dns4/nodalync-bootstrap.eastus.azurecontainer.io/tcp/9000/p2p/12D3KooWMqrUmZm4e1BJTRMWqKHCe1TSX9Vu83uJLEyCGr2dUjYm",
-    "/dns4/nodalync-eu.northeurope.azurecontainer.io/tcp/9000/p2p/12D3KooWQiK8uHf877wena9MAPHHprXkmGRhAmXAYakRsMfdnk7P",
-    "/dns4/nodalync-asia.southeastasia.azurecontainer.io/tcp/9000/p2p/12D3KooWFojioE6LXFs3qqBdKQeCFuMr2obsMrvXGY69jmhheLfk",
-];
+/// Standing orders buying announced content.
+///
+/// Orders are added with `standing-orders add`. Every `interval_secs` a
+/// running node buys up to `max_purchases_per_run` announced items
+/// matching them, writing each to `inbox_dir` (default `inbox` under the
+/// data directory).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StandingOrdersSection {
+    /// Whether to run standing orders.
+    pub enabled: bool,
+    /// Directory bought content is written to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inbox_dir: Option<PathBuf>,
+    /// Most content bought per run.
+    pub max_purchases_per_run: usize,
+    /// How often to run, in seconds.
+    pub interval_secs: u64,
+}
 
-impl Default for NetworkConfigSection {
+impl Default for StandingOrdersSection {
     fn default() -> Self {
+        let defaults = StandingOrderConfig::default();
         Self {
-            enabled: true,
-            listen_addresses: vec!["/ip4/0.0.0.0/tcp/9000".to_string()],
-            bootstrap_nodes: DEFAULT_BOOTSTRAP_NODES
-                .iter()
-                .map(|s| s.to_string())
-                .collect(),
-            gossipsub_propagation_wait: default_gossipsub_propagation_wait(),
+            enabled: defaults.enabled,
+            inbox_dir: None,
+            max_purchases_per_run: defaults.max_purchases_per_run,
+            interval_secs: defaults.interval_secs,
         }
     }
 }
 
-/// Settlement configuration.
+impl StandingOrdersSection {
+    /// Build the ops-layer standing order configuration, with the inbox
+    /// under `base_dir` unless one is set.
+    pub fn ops_config(&self, base_dir: &Path) -> StandingOrderConfig {
+        let inbox_dir = self
+            .inbox_dir
+            .clone()
+            .unwrap_or_else(|| base_dir.join("inbox"));
+        StandingOrderConfig::default()
+            .with_enabled(self.enabled)
+            .with_inbox_dir(inbox_dir)
+            .with_max_purchases_per_run(self.max_purchases_per_run)
+            .with_interval(self.interval_secs)
+    }
+}
+
+/// Moderation policy for content reports from other peers.
+///
+/// Reports are queued for review with `moderation-queue`. Content is only
+/// hidden automatically when `auto_hide_threshold` is set.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
-pub struct SettlementConfig {
-    /// Network to use (e.g., "hedera-testnet", "hedera-mainnet", "mock").
-    pub network: String,
-    /// Account ID for Hedera.
-    pub account_id: Option<String>,
-    /// Path to Hedera private key.
-    pub key_path: Option<PathBuf>,
-    /// Settlement contract ID for Hedera.
-    pub contract_id: Option<String>,
-    /// Enable auto-deposit on startup and when accepting channels.
-    /// When enabled, the node will automatically deposit HBAR to the settlement
-    /// contract to ensure it can accept payment channels from other peers.
-    /// Default: false (opt-in for security).
-    #[serde(default = "default_auto_deposit")]
-    pub auto_deposit: bool,
-    /// Minimum balance to maintain in the settlement contract (in HBAR).
-    /// If the balance falls below this, auto-deposit will trigger.
-    #[serde(default = "default_min_contract_balance")]
-    pub min_contract_balance_hbar: f64,
-    /// Amount to deposit when auto-deposit triggers (in HBAR).
-    #[serde(default = "default_auto_deposit_amount")]
-    pub auto_deposit_amount_hbar: f64,
-    /// Maximum deposit to accept/match when a peer opens a channel (in HBAR).
-    /// Caps how much you'll commit when accepting a channel request.
-    /// This is a security measure to prevent unbounded commitment.
-    #[serde(default = "default_max_accept_deposit")]
-    pub max_accept_deposit_hbar: f64,
+pub struct ModerationPolicyConfig {
+    /// Record reports from other peers.
+    pub accept_reports: bool,
+    /// Hide content after this many reports from reputable peers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_hide_threshold: Option<u32>,
+    /// Minimum reputation for a reporter to count towards the threshold.
+    pub min_reporter_reputation: i64,
 }
 
-fn default_auto_deposit() -> bool {
-    // SECURITY: Default to false (opt-in) to prevent automatic deposits
-    // without explicit user consent. Users must explicitly enable this.
-    false
+impl Default for ModerationPolicyConfig {
+    fn default() -> Self {
+        let defaults = ModerationConfig::default();
+        Self {
+            accept_reports: defaults.accept_reports,
+            auto_hide_threshold: defaults.auto_hide_threshold,
+            min_reporter_reputation: defaults.min_reporter_reputation,
+        }
+    }
 }
 
-fn default_min_contract_balance() -> f64 {
-    100.0 // 100 HBAR minimum
+impl ModerationPolicyConfig {
+    /// Build the ops-layer moderation configuration.
+    pub fn ops_config(&self) -> ModerationConfig {
+        let mut config = ModerationConfig::default()
+            .with_accept_reports(self.accept_reports)
+            .with_min_reporter_reputation(self.min_reporter_reputation);
+        if let Some(threshold) = self.auto_hide_threshold {
+            config = config.with_auto_hide_threshold(threshold);
+        }
+        config
+    }
 }
 
-fn default_auto_deposit_amount() -> f64 {
-    200.0 // Deposit 200 HBAR when triggered
+/// Trust policy for content fetched from other peers.
+///
+/// Without a policy file all content is accepted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrustConfig {
+    /// Path to a trust policy file (see [`TrustPolicyFile`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy_file: Option<PathBuf>,
 }
 
-fn default_max_accept_deposit() -> f64 {
-    500.0 // 500 HBAR max accept deposit per channel
+impl TrustConfig {
+    /// Load the configured policy file and build the ops-layer policy.
+    pub fn ops_policy(&self) -> CliResult<TrustPolicy> {
+        match &self.policy_file {
+            Some(path) => TrustPolicyFile::load(path)?.ops_policy(),
+            None => Ok(TrustPolicy::default()),
+        }
+    }
 }
 
-impl Default for SettlementConfig {
+/// Trust policy file.
+///
+/// Each check is applied only when configured. Content scoring below
+/// `flag_below` (0-100) is flagged, below `reject_below` rejected, and
+/// content failing any check named in `reject_on` is rejected regardless
+/// of its score.
+///
+/// ```toml
+/// min_reputation = 10
+/// require_attestation = true
+/// max_depth = 5
+/// allowed_licenses = ["CC-BY-4.0", "MIT"]
+/// max_price_hbar = 0.5
+/// flag_below = 100
+/// reject_below = 50
+/// reject_on = ["license"]
+///
+/// [weights]
+/// reputation = 2
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrustPolicyFile {
+    /// Minimum publisher reputation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_reputation: Option<i64>,
+    /// Require an attestation claim.
+    pub require_attestation: bool,
+    /// Maximum provenance depth.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<u32>,
+    /// Licenses accepted (case-insensitive); empty accepts any.
+    pub allowed_licenses: Vec<String>,
+    /// Require a declared license.
+    pub require_license: bool,
+    /// Maximum price in HBAR.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_price_hbar: Option<f64>,
+    /// Flag content scoring below this.
+    pub flag_below: u8,
+    /// Reject content scoring below this.
+    pub reject_below: u8,
+    /// Checks whose failure rejects content.
+    pub reject_on: Vec<String>,
+    /// Weights of the checks in the score.
+    pub weights: TrustWeightsFile,
+}
+
+impl Default for TrustPolicyFile {
     fn default() -> Self {
+        let defaults = TrustPolicy::default();
         Self {
-            network: "hedera-testnet".to_string(),
-            account_id: None,
-            key_path: None,
-            contract_id: None,
-            auto_deposit: default_auto_deposit(),
-            min_contract_balance_hbar: default_min_contract_balance(),
-            auto_deposit_amount_hbar: default_auto_deposit_amount(),
-            max_accept_deposit_hbar: default_max_accept_deposit(),
+            min_reputation: None,
+            require_attestation: false,
+            max_depth: None,
+            allowed_licenses: Vec::new(),
+            require_license: false,
+            max_price_hbar: None,
+            flag_below: defaults.flag_below,
+            reject_below: defaults.reject_below,
+            reject_on: Vec::new(),
+            weights: TrustWeightsFile::default(),
         }
     }
 }
 
-/// Economics configuration.
+/// Weights of the trust checks.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(default)]
-pub struct EconomicsConfig {
-    /// Default price for published content (in HBAR).
-    pub default_price: f64,
-    /// Threshold for automatic settlement (in HBAR).
-    pub auto_settle_threshold: f64,
+#[serde(default, deny_unknown_fields)]
+pub struct TrustWeightsFile {
+    /// Weight of the publisher reputation check.
+    pub reputation: u32,
+    /// Weight of the attestation check.
+    pub attestation: u32,
+    /// Weight of the provenance depth check.
+    pub depth: u32,
+    /// Weight of the license check.
+    pub license: u32,
+    /// Weight of the price check.
+    pub price: u32,
 }
 
-impl Default for EconomicsConfig {
+impl Default for TrustWeightsFile {
     fn default() -> Self {
+        let defaults = TrustWeights::default();
         Self {
-            default_price: 0.10,
-            auto_settle_threshold: 100.0,
+            reputation: defaults.reputation,
+            attestation: defaults.attestation,
+            depth: defaults.depth,
+            license: defaults.license,
+            price: defaults.price,
         }
     }
 }
 
-impl EconomicsConfig {
-    /// Convert HBAR to tinybars (10^-8 HBAR).
-    pub fn default_price_units(&self) -> u64 {
-        hbar_to_tinybars(self.default_price)
+impl TrustPolicyFile {
+    /// Load a policy file.
+    pub fn load(path: &Path) -> CliResult<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            CliError::config(format!(
+                "Failed to read trust policy {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        toml::from_str(&contents).map_err(|e| {
+            CliError::config(format!("Invalid trust policy {}: {}", path.display(), e))
+        })
     }
 
-    /// Convert HBAR to tinybars (10^-8 HBAR).
-    pub fn auto_settle_threshold_units(&self) -> u64 {
-        ndl_to_units(self.auto_settle_threshold)
+    /// Build the ops-layer trust policy.
+    pub fn ops_policy(&self) -> CliResult<TrustPolicy> {
+        if self.flag_below > 100 || self.reject_below > 100 {
+            return Err(CliError::config("Trust policy thresholds must be 0-100"));
+        }
+        let reject_on = self
+            .reject_on
+            .iter()
+            .map(|name| name.parse::<TrustCheck>().map_err(CliError::config))
+            .collect::<CliResult<Vec<_>>>()?;
+
+        let mut policy = TrustPolicy::default()
+            .with_require_attestation(self.require_attestation)
+            .with_allowed_licenses(self.allowed_licenses.clone())
+            .with_require_license(self.require_license)
+            .with_weights(TrustWeights {
+                reputation: self.weights.reputation,
+                attestation: self.weights.attestation,
+                depth: self.weights.depth,
+                license: self.weights.license,
+                price: self.weights.price,
+            })
+            .with_thresholds(self.flag_below, self.reject_below)
+            .with_reject_on(reject_on);
+        if let Some(min) = self.min_reputation {
+            policy = policy.with_min_reputation(min);
+        }
+        if let Some(max) = self.max_depth {
+            policy = policy.with_max_depth(max);
+        }
+        if let Some(max) = self.max_price_hbar {
+            policy = policy.with_max_price(hbar_to_tinybars(max));
+        }
+        Ok(policy)
     }
 }
 
@@ -489,6 +1916,102 @@ pub struct HeartbeatConfig {
     pub include_metrics: bool,
 }
 
+// =============================================================================
+// Logging Configuration
+// =============================================================================
+
+/// Log file configuration.
+///
+/// The node writes structured JSON logs to `<base_dir>/logs/`, independent of
+/// the stderr output controlled by `--verbose` and `RUST_LOG`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Whether to write log files.
+    pub file: bool,
+    /// Default level for the log file (trace, debug, info, warn, error).
+    pub level: String,
+    /// Per-module level overrides (e.g. `nodalync_net = "debug"`).
+    pub modules: BTreeMap<String, String>,
+    /// Size at which the current log file is rotated (MB).
+    pub max_file_size_mb: u64,
+    /// Number of rotated files to keep.
+    pub max_files: u32,
+    /// Trace export to an OTLP collector.
+    pub otlp: OtlpConfig,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            file: true,
+            level: "info".to_string(),
+            modules: BTreeMap::new(),
+            max_file_size_mb: 10,
+            max_files: 5,
+            otlp: OtlpConfig::default(),
+        }
+    }
+}
+
+/// Trace and metrics export over OTLP/HTTP, for viewing traces in Jaeger
+/// and metrics in Prometheus and the like.
+///
+/// Each is off unless its endpoint is set. Needs a build with the `otlp`
+/// feature. Metrics are exported by the MCP server only.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OtlpConfig {
+    /// Collector traces endpoint (e.g. `http://localhost:4318/v1/traces`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// Service name the traces are reported under.
+    pub service_name: String,
+    /// Level of the spans to export (trace, debug, info, warn, error).
+    pub level: String,
+    /// Collector metrics endpoint (e.g. `http://localhost:4318/v1/metrics`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics_endpoint: Option<String>,
+    /// Seconds between metrics exports.
+    pub metrics_interval_secs: u64,
+}
+
+impl Default for OtlpConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            service_name: "nodalync".to_string(),
+            level: "info".to_string(),
+            metrics_endpoint: None,
+            metrics_interval_secs: 60,
+        }
+    }
+}
+
+impl OtlpConfig {
+    /// Build the MCP server metrics configuration.
+    pub fn metrics_config(&self) -> MetricsConfig {
+        MetricsConfig {
+            endpoint: self.metrics_endpoint.clone(),
+            service_name: self.service_name.clone(),
+            export_interval_secs: self.metrics_interval_secs,
+        }
+    }
+}
+
+impl LoggingConfig {
+    /// Build the filter directive string for the log file.
+    pub fn filter_directives(&self) -> String {
+        let mut directives = vec![self.level.clone()];
+        directives.extend(
+            self.modules
+                .iter()
+                .map(|(module, level)| format!("{}={}", module, level)),
+        );
+        directives.join(",")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -501,6 +2024,39 @@ mod tests {
         assert_eq!(config.settlement.network, "hedera-testnet");
     }
 
+    #[test]
+    fn test_bootstrap_mode_config() {
+        assert!(!CliConfig::default().network.bootstrap_mode);
+
+        let parsed: CliConfig = toml::from_str("[network]\nbootstrap_mode = true\n").unwrap();
+        assert!(parsed.network.bootstrap_mode);
+        // Unspecified fields keep their defaults
+        assert!(parsed.network.enabled);
+    }
+
+    #[test]
+    fn test_dns_bootstrap_config() {
+        let cache = Path::new("/tmp/bootstrap.json");
+        assert!(CliConfig::default()
+            .network
+            .dns_bootstrap_config(cache)
+            .is_none());
+
+        let parsed: CliConfig = toml::from_str(
+            "[network]\ndns_bootstrap = \"bootstrap.example.\"\ndns_bootstrap_cache_hours = 2\n",
+        )
+        .unwrap();
+        let dns = parsed.network.dns_bootstrap_config(cache).unwrap();
+        assert_eq!(dns.domain, "bootstrap.example");
+        assert_eq!(dns.cache_path.as_deref(), Some(cache));
+        assert_eq!(dns.cache_ttl, Duration::from_secs(7_200));
+        // The static list stays as the fallback
+        assert!(!parsed.network.bootstrap_nodes.is_empty());
+
+        let parsed: CliConfig = toml::from_str("[network]\ndns_bootstrap = \" \"\n").unwrap();
+        assert!(parsed.network.dns_bootstrap_config(cache).is_none());
+    }
+
     #[test]
     fn test_hbar_conversion() {
         assert_eq!(hbar_to_tinybars(1.0), 100_000_000);
@@ -613,6 +2169,539 @@ mod tests {
         assert_eq!(config.settlement.network, default.settlement.network);
     }
 
+    #[test]
+    fn test_announcements_filter_config() {
+        let defaults = AnnouncementsConfig::default().filter_config();
+        assert_eq!(defaults.max_per_peer_per_minute, Some(60));
+        assert_eq!(defaults.max_price, nodalync_types::MAX_PRICE);
+        assert!(defaults.min_reputation.is_none());
+
+        let config: CliConfig = toml::from_str(
+            r#"
+            [announcements]
+            min_reputation = 5
+            max_price = 2.5
+            title_blocklist = ["casino"]
+            max_per_peer_per_minute = 0
+            "#,
+        )
+        .unwrap();
+        let filter = config.announcements.filter_config();
+        assert_eq!(filter.min_reputation, Some(5));
+        assert_eq!(filter.max_price, 250_000_000);
+        assert_eq!(filter.title_blocklist, vec!["casino"]);
+        assert!(filter.max_per_peer_per_minute.is_none());
+        assert!(filter.collapse_duplicate_titles);
+        assert_eq!(
+            config.announcements.ingest_config(),
+            AnnouncementIngestConfig::default()
+        );
+    }
+
+    #[test]
+    fn test_settlement_top_up_config() {
+        let defaults = SettlementConfig::default().top_up_config();
+        assert!(!defaults.enabled);
+        assert_eq!(defaults.daily_cap, hbar_to_tinybars(200.0));
+
+        let config: CliConfig = toml::from_str(
+            r#"
+            [settlement]
+            auto_top_up = true
+            top_up_threshold_hbar = 5.0
+            top_up_amount_hbar = 20.0
+            top_up_daily_cap_hbar = 60.0
+            "#,
+        )
+        .unwrap();
+        let top_up = config.settlement.top_up_config();
+        assert!(top_up.enabled);
+        assert_eq!(top_up.min_balance, 500_000_000);
+        assert_eq!(top_up.amount, 2_000_000_000);
+        assert_eq!(top_up.daily_cap, 6_000_000_000);
+        assert_eq!(top_up.check_interval_secs, 60);
+    }
+
+    #[test]
+    fn test_usage_reports_config() {
+        let defaults = UsageReportsConfig::default().ops_config();
+        assert!(!defaults.send);
+        assert!(!defaults.accept);
+
+        let config: CliConfig = toml::from_str(
+            r#"
+            [usage_reports]
+            send = true
+            max_per_peer_per_hour = 3
+            "#,
+        )
+        .unwrap();
+        let usage = config.usage_reports.ops_config();
+        assert!(usage.send);
+        assert!(!usage.accept);
+        assert_eq!(usage.max_per_peer_per_hour, 3);
+    }
+
+    #[test]
+    fn test_query_challenge_config() {
+        let defaults = QueryChallengesConfig::default().ops_config();
+        assert_eq!(defaults.min_price, None);
+        assert_eq!(defaults.ttl_ms, 60_000);
+
+        let config: CliConfig = toml::from_str(
+            r#"
+            [query_challenge]
+            min_price = 0.5
+            ttl_secs = 30
+            "#,
+        )
+        .unwrap();
+        let challenge = config.query_challenge.ops_config();
+        assert_eq!(challenge.min_price, Some(5000_0000));
+        assert_eq!(challenge.ttl_ms, 30_000);
+    }
+
+    #[test]
+    fn test_bonds_config() {
+        let defaults = BondsConfig::default().ops_config();
+        assert!(defaults.requirements.is_empty());
+        assert!(!defaults.slash_on_fraud);
+        assert_eq!(defaults.cache_ttl_ms, 300_000);
+
+        let config: CliConfig = toml::from_str(
+            r#"
+            [bonds]
+            shared = 10.0
+            slash_on_fraud = true
+            cache_ttl_secs = 60
+            "#,
+        )
+        .unwrap();
+        let bonds = config.bonds.ops_config();
+        assert_eq!(bonds.requirements.unlisted, 0);
+        assert_eq!(bonds.requirements.shared, 10_0000_0000);
+        assert!(bonds.slash_on_fraud);
+        assert_eq!(bonds.cache_ttl_ms, 60_000);
+    }
+
+    #[test]
+    fn test_retention_config() {
+        let defaults = RetentionPolicyConfig::default().ops_config();
+        assert!(defaults.ttls.is_empty());
+
+        let config: CliConfig = toml::from_str(
+            r#"
+            [retention]
+            announcements_days = 30
+            analytics_days = 90
+            check_interval_secs = 600
+            "#,
+        )
+        .unwrap();
+        let retention = config.retention.ops_config();
+        assert_eq!(
+            retention.ttl(RetentionCategory::Announcements),
+            Some(30 * 86_400)
+        );
+        assert_eq!(
+            retention.ttl(RetentionCategory::Analytics),
+            Some(90 * 86_400)
+        );
+        assert_eq!(retention.ttl(RetentionCategory::PeerRecords), None);
+        assert_eq!(retention.check_interval_secs, 600);
+    }
+
+    #[test]
+    fn test_clock_config() {
+        let defaults = ClockConfig::default().ops_config();
+        assert_eq!(defaults, ClockSkewConfig::default());
+
+        let config: CliConfig = toml::from_str(
+            r#"
+            [clock]
+            correct = false
+            tolerance_secs = 5
+            min_peers = 1
+            probe_interval_secs = 60
+            "#,
+        )
+        .unwrap();
+        let clock = config.clock.ops_config();
+        assert!(!clock.correct);
+        assert_eq!(clock.tolerance_ms, 5_000);
+        assert_eq!((clock.min_peers, clock.max_peers), (1, 8));
+        assert_eq!(clock.probe_interval_secs, 60);
+    }
+
+    #[test]
+    fn test_qos_config() {
+        let defaults = QosSection::default().ops_config();
+        assert_eq!(defaults, QosConfig::default());
+
+        let config: CliConfig = toml::from_str(
+            r#"
+            [qos]
+            enabled = false
+            probe_interval_secs = 30
+            weight = 2.0
+            "#,
+        )
+        .unwrap();
+        let qos = config.qos.ops_config();
+        assert!(!qos.enabled);
+        assert_eq!(qos.max_peers, 16);
+        assert_eq!(qos.probe_interval_secs, 30);
+        assert_eq!(qos.weight, 1.0);
+        assert_eq!(config.ops_config().unwrap().qos, qos);
+    }
+
+    #[test]
+    fn test_availability_config() {
+        let defaults = AvailabilitySection::default().ops_config().unwrap();
+        assert_eq!(defaults, AvailabilityConfig::default());
+
+        let checker = nodalync_crypto::peer_id_to_string(&nodalync_crypto::PeerId([4u8; 20]));
+        let config: CliConfig = toml::from_str(&format!(
+            r#"
+            [availability]
+            enabled = true
+            sample_size = 5
+            checkers = ["{}"]
+            "#,
+            checker
+        ))
+        .unwrap();
+        let availability = config.availability.ops_config().unwrap();
+        assert!(availability.enabled);
+        assert_eq!(availability.sample_size, 5);
+        assert_eq!(
+            availability.checkers,
+            vec![nodalync_crypto::PeerId([4u8; 20])]
+        );
+        assert_eq!(config.ops_config().unwrap().availability, availability);
+
+        let mut config = config;
+        config.availability.checkers = vec!["not-a-peer".to_string()];
+        assert!(config.ops_config().is_err());
+    }
+
+    #[test]
+    fn test_snapshot_config() {
+        let defaults = SnapshotSection::default();
+        assert!(defaults.sync_on_start);
+        assert_eq!(defaults.ops_config(), SnapshotConfig::default());
+
+        let config: CliConfig = toml::from_str(
+            r#"
+            [snapshot]
+            serve = true
+            sync_on_start = false
+            max_age_secs = 60
+            "#,
+        )
+        .unwrap();
+        assert!(!config.snapshot.sync_on_start);
+        let snapshot = config.snapshot.ops_config();
+        assert!(snapshot.serve);
+        assert_eq!(snapshot.max_age_ms, 60_000);
+        assert_eq!(snapshot.dial_peers, 8);
+    }
+
+    #[test]
+    fn test_entitlements_config() {
+        let defaults = EntitlementsSection::default();
+        assert_eq!(defaults.window_days, 30);
+        assert_eq!(defaults.ops_config(), EntitlementConfig::default());
+
+        let config: CliConfig = toml::from_str(
+            r#"
+            [entitlements]
+            enabled = false
+            window_days = 7
+            "#,
+        )
+        .unwrap();
+        let entitlements = config.ops_config().unwrap().entitlements;
+        assert!(!entitlements.enabled);
+        assert_eq!(entitlements.window_ms, 7 * 86_400_000);
+    }
+
+    #[test]
+    fn test_disputes_config() {
+        let defaults = DisputesSection::default();
+        assert_eq!(defaults.warn_before_hours, 6);
+        assert_eq!(defaults.ops_config(), DisputeConfig::default());
+
+        let config: CliConfig = toml::from_str(
+            r#"
+            [disputes]
+            auto_counter = false
+            warn_before_hours = 12
+            check_interval_secs = 0
+            "#,
+        )
+        .unwrap();
+        let disputes = config.ops_config().unwrap().disputes;
+        assert!(disputes.enabled);
+        assert!(!disputes.auto_counter);
+        assert_eq!(disputes.warn_before_ms, 12 * 3_600_000);
+        assert_eq!(disputes.check_interval_secs, 1);
+    }
+
+    #[test]
+    fn test_storefront_config() {
+        let defaults = StorefrontSection::default().ops_config().unwrap();
+        assert_eq!(defaults, StorefrontConfig::default());
+
+        let hash = nodalync_crypto::content_hash(b"featured");
+        let config: CliConfig = toml::from_str(&format!(
+            r#"
+            [storefront]
+            enabled = true
+            name = "Field Guides"
+            featured = ["{}"]
+            max_age_secs = 60
+            "#,
+            hash
+        ))
+        .unwrap();
+        let storefront = config.storefront.ops_config().unwrap();
+        assert!(storefront.enabled);
+        assert_eq!(storefront.name, "Field Guides");
+        assert_eq!(storefront.featured, vec![hash]);
+        assert_eq!(storefront.max_age_ms, 60_000);
+        assert_eq!(config.ops_config().unwrap().storefront, storefront);
+
+        let mut config = config;
+        config.storefront.featured = vec!["not-a-hash".to_string()];
+        assert!(config.ops_config().is_err());
+    }
+
+    #[test]
+    fn test_popularity_config() {
+        let defaults = PopularitySection::default();
+        assert_eq!(defaults.half_life_hours, 24);
+        assert_eq!(defaults.ops_config(), PopularityConfig::default());
+
+        let config: CliConfig = toml::from_str(
+            r#"
+            [popularity]
+            half_life_hours = 6
+            hot_manifests = 16
+            fetch = true
+            max_fetch = 4
+            "#,
+        )
+        .unwrap();
+        let popularity = config.popularity.ops_config();
+        assert!(popularity.enabled);
+        assert_eq!(popularity.half_life_ms, 6 * 3_600_000);
+        assert_eq!(popularity.hot_manifests, 16);
+        assert!(popularity.fetch);
+        assert_eq!(popularity.max_fetch, 4);
+        assert_eq!(popularity.prewarm_interval_secs, 300);
+    }
+
+    #[test]
+    fn test_standing_orders_config() {
+        let base_dir = Path::new("/data/nodalync");
+        let defaults = StandingOrdersSection::default().ops_config(base_dir);
+        assert!(!defaults.enabled);
+        assert_eq!(defaults.inbox_dir, Some(base_dir.join("inbox")));
+        assert_eq!(defaults.interval_secs, 60);
+
+        let config: CliConfig = toml::from_str(
+            r#"
+            [standing_orders]
+            enabled = true
+            inbox_dir = "/tmp/inbox"
+            max_purchases_per_run = 2
+            "#,
+        )
+        .unwrap();
+        let standing_orders = config.standing_orders.ops_config(base_dir);
+        assert!(standing_orders.enabled);
+        assert_eq!(standing_orders.inbox_dir, Some(PathBuf::from("/tmp/inbox")));
+        assert_eq!(standing_orders.max_purchases_per_run, 2);
+    }
+
+    #[test]
+    fn test_notifications_config() {
+        let defaults = NotificationsConfig::default();
+        assert_eq!(defaults.ops_config(), NotificationConfig::default());
+        assert!(defaults.email.is_none());
+
+        let config: CliConfig = toml::from_str(
+            r#"
+            [notifications]
+            weekly = true
+            top_content = 3
+
+            [notifications.email]
+            smtp_host = "smtp.example.com"
+            username = "node"
+            password = "secret"
+            from = "node@example.com"
+            to = ["me@example.com"]
+
+            [[notifications.webhooks]]
+            url = "https://hooks.slack.com/services/T/B/X"
+            webhook_type = "slack"
+            "#,
+        )
+        .unwrap();
+        let notifications = config.notifications.ops_config();
+        assert_eq!(notifications.periods(), vec![DigestPeriod::Weekly]);
+        assert_eq!(notifications.top_content, 3);
+        let email = config.notifications.email.as_ref().unwrap();
+        assert_eq!(email.smtp_port, 587);
+        assert_eq!(email.tls, SmtpTls::Starttls);
+        assert_eq!(email.to, vec!["me@example.com".to_string()]);
+        assert_eq!(
+            config.notifications.webhooks[0].webhook_type,
+            WebhookType::Slack
+        );
+        assert_eq!(config.ops_config().unwrap().notifications, notifications);
+    }
+
+    #[test]
+    fn test_bridge_config() {
+        let defaults = BridgeConfig::default();
+        assert!(!defaults.enabled);
+        assert_eq!(defaults.base_url(), "http://127.0.0.1:8090");
+
+        let config: CliConfig = toml::from_str(
+            r#"
+            [bridge]
+            enabled = true
+            public_url = "https://node.example.com/"
+            feeds = ["https://blog.example.com/feed.xml"]
+            "#,
+        )
+        .unwrap();
+        assert!(config.bridge.enabled);
+        assert_eq!(config.bridge.base_url(), "https://node.example.com");
+        assert_eq!(config.bridge.feeds.len(), 1);
+        assert_eq!(config.bridge.poll_interval_secs, 3600);
+    }
+
+    #[test]
+    fn test_relay_config() {
+        let defaults = RelayConfigSection::default().net_config().unwrap();
+        assert!(defaults.route.is_empty());
+        assert!(!defaults.serve);
+
+        let relay = nodalync_crypto::PeerId([7u8; 20]);
+        let config: CliConfig = toml::from_str(&format!(
+            r#"
+            [network.relay]
+            route = ["{}"]
+            route_fee_hbar = 0.01
+            serve = true
+            fee_hbar = 0.02
+            "#,
+            nodalync_crypto::peer_id_to_string(&relay)
+        ))
+        .unwrap();
+        let relay_config = config.network.relay.net_config().unwrap();
+        assert_eq!(relay_config.route, vec![relay]);
+        assert_eq!(relay_config.route_fee, 1_000_000);
+        assert!(relay_config.serve);
+        assert_eq!(relay_config.fee, 2_000_000);
+        // Unspecified network fields keep their defaults
+        assert!(config.network.enabled);
+
+        let mut bad = config.network.relay.clone();
+        bad.route = vec!["not-a-peer".to_string()];
+        assert!(bad.net_config().is_err());
+        bad.route = vec![nodalync_crypto::peer_id_to_string(&relay); 3];
+        assert!(bad.net_config().is_err());
+    }
+
+    #[test]
+    fn test_fraud_proofs_config() {
+        let defaults = FraudProofsConfig::default().ops_config();
+        assert!(defaults.accept_proofs);
+        assert_eq!(defaults.reputation_penalty, 10);
+        assert!(!defaults.submit_on_chain);
+
+        let config: CliConfig = toml::from_str(
+            r#"
+            [fraud_proofs]
+            reputation_penalty = 25
+            submit_on_chain = true
+            "#,
+        )
+        .unwrap();
+        let fraud_proofs = config.fraud_proofs.ops_config();
+        assert!(fraud_proofs.accept_proofs);
+        assert_eq!(fraud_proofs.reputation_penalty, 25);
+        assert!(fraud_proofs.submit_on_chain);
+    }
+
+    #[test]
+    fn test_moderation_config() {
+        let defaults = ModerationPolicyConfig::default().ops_config();
+        assert!(defaults.accept_reports);
+        assert_eq!(defaults.auto_hide_threshold, None);
+
+        let config: CliConfig = toml::from_str(
+            r#"
+            [moderation]
+            auto_hide_threshold = 3
+            min_reporter_reputation = 20
+            "#,
+        )
+        .unwrap();
+        let moderation = config.moderation.ops_config();
+        assert!(moderation.accept_reports);
+        assert_eq!(moderation.auto_hide_threshold, Some(3));
+        assert_eq!(moderation.min_reporter_reputation, 20);
+    }
+
+    #[test]
+    fn test_trust_policy_file() {
+        assert_eq!(
+            TrustConfig::default().ops_policy().unwrap(),
+            TrustPolicy::default()
+        );
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("trust.toml");
+        std::fs::write(
+            &path,
+            r#"
+            min_reputation = 10
+            allowed_licenses = ["CC-BY-4.0"]
+            max_price_hbar = 0.5
+            reject_below = 50
+            reject_on = ["license"]
+
+            [weights]
+            reputation = 3
+            "#,
+        )
+        .unwrap();
+        let config: CliConfig = toml::from_str(&format!(
+            "[trust]\npolicy_file = {:?}\n",
+            path.display().to_string()
+        ))
+        .unwrap();
+        let policy = config.trust.ops_policy().unwrap();
+        assert_eq!(policy.min_reputation, Some(10));
+        assert_eq!(policy.allowed_licenses, vec!["CC-BY-4.0".to_string()]);
+        assert_eq!(policy.max_price, Some(hbar_to_tinybars(0.5)));
+        assert_eq!(policy.reject_below, 50);
+        assert_eq!(policy.reject_on, vec![TrustCheck::License]);
+        assert_eq!(policy.weights.reputation, 3);
+        assert_eq!(policy.weights.price, 1);
+
+        std::fs::write(&path, "reject_on = [\"size\"]").unwrap();
+        assert!(config.trust.ops_policy().is_err());
+        std::fs::write(&path, "max_size = 10").unwrap();
+        assert!(config.trust.ops_policy().is_err());
+    }
+
     #[test]
     fn test_economics_default_price_units() {
         let econ = EconomicsConfig::default();
@@ -636,6 +2725,21 @@ mod tests {
         assert_eq!(base, storage.content_dir.parent().unwrap().to_path_buf());
     }
 
+    #[test]
+    fn test_storage_database_url() {
+        let config = CliConfig::default();
+        assert!(config.storage.database_url.is_none());
+        assert!(!toml::to_string(&config).unwrap().contains("database_url"));
+
+        let mut config = CliConfig::default();
+        config.storage.database_url = Some("postgres://nodalync@db/nodalync".to_string());
+        let parsed: CliConfig = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(
+            parsed.storage.database_url.as_deref(),
+            Some("postgres://nodalync@db/nodalync")
+        );
+    }
+
     #[test]
     fn test_alerting_config_defaults() {
         let alerting = AlertingConfig::default();
@@ -646,6 +2750,67 @@ mod tests {
         assert!(alerting.heartbeat.is_none());
     }
 
+    #[test]
+    fn test_logging_config() {
+        let logging = LoggingConfig::default();
+        assert!(logging.file);
+        assert_eq!(logging.filter_directives(), "info");
+
+        let parsed: CliConfig = toml::from_str(
+            "[logging]\nlevel = \"warn\"\n\n[logging.modules]\nnodalync_net = \"debug\"\n",
+        )
+        .unwrap();
+        assert_eq!(parsed.logging.max_files, 5);
+        assert_eq!(
+            parsed.logging.filter_directives(),
+            "warn,nodalync_net=debug"
+        );
+        assert_eq!(parsed.logging.otlp.endpoint, None);
+
+        let parsed: CliConfig =
+            toml::from_str("[logging.otlp]\nendpoint = \"http://localhost:4318/v1/traces\"\n")
+                .unwrap();
+        assert_eq!(
+            parsed.logging.otlp.endpoint.as_deref(),
+            Some("http://localhost:4318/v1/traces")
+        );
+        assert_eq!(parsed.logging.otlp.service_name, "nodalync");
+        assert_eq!(parsed.logging.otlp.metrics_config().endpoint, None);
+
+        let parsed: CliConfig = toml::from_str(
+            "[logging.otlp]\nmetrics_endpoint = \"http://localhost:4318/v1/metrics\"\nmetrics_interval_secs = 15\n",
+        )
+        .unwrap();
+        let metrics = parsed.logging.otlp.metrics_config();
+        assert_eq!(
+            metrics.endpoint.as_deref(),
+            Some("http://localhost:4318/v1/metrics")
+        );
+        assert_eq!(metrics.export_interval_secs, 15);
+        assert_eq!(metrics.service_name, "nodalync");
+    }
+
+    #[test]
+    fn test_ops_overrides() {
+        let config = CliConfig::default();
+        assert!(config.ops.is_empty());
+        let ops = config.ops_config().unwrap();
+        assert_eq!(ops.search.max_peers, OpsConfig::default().search.max_peers);
+
+        let parsed: CliConfig =
+            toml::from_str("[settlement]\nauto_rebalance = true\n\n[ops.search]\nmax_peers = 3\n")
+                .unwrap();
+        let ops = parsed.ops_config().unwrap();
+        assert_eq!(ops.search.max_peers, 3);
+        // Sections outside [ops] still apply
+        assert!(ops.rebalance.auto_rebalance);
+
+        let parsed: CliConfig = toml::from_str("[ops.close_batch]\nmax_concurrent = 0\n").unwrap();
+        assert!(parsed.ops_config().is_err());
+        let parsed: CliConfig = toml::from_str("[ops.search]\nmax_peerz = 3\n").unwrap();
+        assert!(parsed.ops_config().is_err());
+    }
+
     #[test]
     fn test_expand_env_vars() {
         std::env::set_var("TEST_WEBHOOK_URL", "https://example.com/webhook");
@@ -1,21 +1,37 @@
 //! Node context for CLI operations.
 
+use std::path::Path;
 use std::sync::Arc;
 
 use nodalync_crypto::{PeerId, PrivateKey, PublicKey};
-use nodalync_net::{Network, NetworkConfig, NetworkNode};
-use nodalync_ops::{ChannelConfig, DefaultNodeOperations, OpsConfig};
+use nodalync_net::{
+    CaptureConfig, Network, NetworkConfig, NetworkNode, RateLimitConfig, ReplayLogConfig,
+};
+use nodalync_ops::DefaultNodeOperations;
 use nodalync_settle::Settlement;
 use nodalync_store::{NodeState, NodeStateConfig};
 
-use crate::config::hbar_to_tinybars;
-
 #[cfg(feature = "hedera-sdk")]
 use nodalync_settle::{HederaConfig, HederaSettlement};
 
 use crate::config::CliConfig;
 use crate::error::{CliError, CliResult};
+use crate::notifications::build_notifiers;
 use crate::prompt::get_identity_password;
+use crate::webhooks::HttpWebhookTransport;
+
+/// File (under the base directory) that bootstrap nodes persist DHT records to.
+pub const DHT_RECORDS_FILE: &str = "dht_records.bin";
+
+/// File (under the base directory) the replay log is written to.
+pub const REPLAY_LOG_FILE: &str = "replay.log";
+
+/// File (under the base directory) the session capture is written to.
+pub const CAPTURE_FILE: &str = "capture.ndlcap";
+
+/// File (under the base directory) bootstrap nodes resolved from DNS are
+/// cached in.
+pub const BOOTSTRAP_CACHE_FILE: &str = "bootstrap_dns.json";
 
 /// Create settlement instance based on configuration.
 ///
@@ -116,6 +132,16 @@ async fn create_hedera_settlement(
     Ok(Arc::new(settlement))
 }
 
+/// Node state configuration for the base directory, with shared stores in
+/// Postgres when `database_url` is set.
+fn state_config(config: &CliConfig, base_dir: &Path) -> NodeStateConfig {
+    let state_config = NodeStateConfig::new(base_dir);
+    match config.storage.database_url {
+        Some(ref url) => state_config.with_database_url(url),
+        None => state_config,
+    }
+}
+
 /// Convert a nodalync private key to a libp2p keypair.
 ///
 /// Both use Ed25519, so the 32-byte seed can be used directly.
@@ -151,12 +177,29 @@ pub struct NodeContext {
 impl NodeContext {
     /// Initialize node context for local-only operations.
     ///
-    /// Does not start networking or settlement. Use this for commands like `list`, `versions`.
+    /// Does not start networking or settlement. Use this for commands like `delete`, `update`.
+    ///
+    /// Fails with a store `WriteLocked` error if a running node holds the
+    /// database write lock.
     pub fn local(config: CliConfig) -> CliResult<Self> {
+        Self::open_local(config, false)
+    }
+
+    /// Initialize node context for read-only local operations.
+    ///
+    /// Like [`NodeContext::local`], but falls back to a read-only view of the
+    /// database if a running node holds the write lock. Use this for commands
+    /// that only display state, like `list`, `versions`.
+    pub fn local_read_only(config: CliConfig) -> CliResult<Self> {
+        Self::open_local(config, true)
+    }
+
+    fn open_local(config: CliConfig, read_only_fallback: bool) -> CliResult<Self> {
         let base_dir = config.base_dir();
 
         // Open storage
-        let state_config = NodeStateConfig::new(&base_dir);
+        let state_config =
+            state_config(&config, &base_dir).with_read_only_fallback(read_only_fallback);
         let state = NodeState::open(state_config)?;
 
         // Get peer ID (must exist)
@@ -188,7 +231,8 @@ impl NodeContext {
         let base_dir = config.base_dir();
 
         // Open storage
-        let state_config = NodeStateConfig::new(&base_dir);
+        let index_bytes = config.storage.manifest_index_mb as usize * 1024 * 1024;
+        let state_config = state_config(&config, &base_dir).with_manifest_index_bytes(index_bytes);
         let state = NodeState::open(state_config)?;
 
         // Get peer ID (must exist)
@@ -248,6 +292,36 @@ impl NodeContext {
                 }
             }
 
+            if let Some(dns) = config
+                .network
+                .dns_bootstrap_config(&base_dir.join(BOOTSTRAP_CACHE_FILE))
+            {
+                net_config = net_config.with_dns_bootstrap(dns);
+            }
+
+            net_config = net_config.with_relay(config.network.relay.net_config()?);
+
+            if config.network.replay_log {
+                net_config = net_config.with_replay_log(
+                    ReplayLogConfig::new(base_dir.join(REPLAY_LOG_FILE))
+                        .with_max_bytes(config.network.replay_log_max_mb * 1024 * 1024),
+                );
+            }
+            if config.network.capture {
+                net_config =
+                    net_config.with_capture(CaptureConfig::new(base_dir.join(CAPTURE_FILE)));
+            }
+
+            // Bootstrap/relay nodes are public infrastructure: limit each
+            // remote IP, keep DHT records across restarts, and serve no content
+            if config.network.bootstrap_mode {
+                net_config = net_config
+                    .with_rate_limit(RateLimitConfig::default())
+                    .with_dht_persistence(base_dir.join(DHT_RECORDS_FILE))
+                    .with_serve_requests(false);
+                tracing::info!("Bootstrap mode: payments and content serving disabled");
+            }
+
             // Convert nodalync keypair to libp2p keypair for consistent peer ID
             let libp2p_keypair = to_libp2p_keypair(&private_key)?;
             let node = NetworkNode::with_keypair(
@@ -262,29 +336,22 @@ impl NodeContext {
             None
         };
 
-        // Create settlement based on config (may fail if not configured)
-        let settlement = match create_settlement(&config).await {
-            Ok(s) => Some(s),
-            Err(e) => {
-                tracing::warn!("Failed to create settlement: {}", e);
-                None
+        // Create settlement based on config (may fail if not configured).
+        // Bootstrap nodes never take payments.
+        let settlement = if config.network.bootstrap_mode {
+            None
+        } else {
+            match create_settlement(&config).await {
+                Ok(s) => Some(s),
+                Err(e) => {
+                    tracing::warn!("Failed to create settlement: {}", e);
+                    None
+                }
             }
         };
 
-        // Build OpsConfig from CLI settlement values
-        let ops_config = OpsConfig::default().with_channel(
-            ChannelConfig::default()
-                .with_max_accept_deposit(hbar_to_tinybars(
-                    config.settlement.max_accept_deposit_hbar,
-                ))
-                .with_auto_deposit(config.settlement.auto_deposit)
-                .with_auto_deposit_amount(hbar_to_tinybars(
-                    config.settlement.auto_deposit_amount_hbar,
-                ))
-                .with_auto_deposit_min_balance(hbar_to_tinybars(
-                    config.settlement.min_contract_balance_hbar,
-                )),
-        );
+        // Build OpsConfig from the CLI sections and [ops] overrides
+        let ops_config = config.ops_config()?;
 
         // Create operations with network and/or settlement using config variants
         let mut ops = match (&network, &settlement) {
@@ -315,6 +382,18 @@ impl NodeContext {
             ops.set_private_key(private_key.clone());
         }
 
+        // Post event webhooks when endpoints are registered
+        if ops.config.webhooks.is_enabled() {
+            ops.set_webhook_transport(Arc::new(HttpWebhookTransport::new()));
+        }
+
+        // Send activity digests when a period is enabled
+        if ops.config.notifications.is_enabled() {
+            for notifier in build_notifiers(&config.notifications)? {
+                ops.add_notifier(notifier);
+            }
+        }
+
         Ok(Self {
             ops,
             settlement,
@@ -334,8 +413,7 @@ impl NodeContext {
         std::fs::create_dir_all(&base_dir)?;
 
         // Open storage
-        let state_config = NodeStateConfig::new(&base_dir);
-        let state = NodeState::open(state_config)?;
+        let state = NodeState::open(state_config(&config, &base_dir))?;
 
         Ok(state)
     }
@@ -102,7 +102,16 @@ impl CliError {
     }
 
     /// Get the exit code for this error.
+    ///
+    /// Errors wrapped from other crates exit with the code of their
+    /// protocol error when it names a not-found, payment or network
+    /// failure, and with the code of their crate otherwise.
     pub fn exit_code(&self) -> i32 {
+        if let Self::Ops(_) | Self::Network(_) | Self::Store(_) | Self::Settlement(_) = self {
+            if let Some(code) = protocol_exit_code(self.error_code()) {
+                return code;
+            }
+        }
         match self {
             // User errors: 1
             Self::User(_)
@@ -158,13 +167,11 @@ impl CliError {
             // Node state errors
             Self::NodeNotRunning | Self::NodeAlreadyRunning => ErrorCode::ConnectionFailed,
 
-            // Network errors
-            Self::Network(_) => ErrorCode::ConnectionFailed,
-
             // Delegated errors
             Self::Ops(e) => e.error_code(),
-            Self::Settlement(_) => ErrorCode::InternalError,
-            Self::Store(_) => ErrorCode::InternalError,
+            Self::Network(e) => e.error_code(),
+            Self::Settlement(e) => e.error_code(),
+            Self::Store(e) => e.error_code(),
             Self::Io(_) => ErrorCode::InternalError,
 
             // User-facing errors
@@ -183,6 +190,10 @@ impl CliError {
         match self {
             Self::User(_) => None, // message itself is the guidance
             Self::Config(_) => None, // config errors include actionable details
+            Self::Store(
+                nodalync_store::StoreError::WriteLocked { .. }
+                | nodalync_store::StoreError::ReadOnly(_),
+            ) => Some("Another process (usually a running node or MCP server) is using the database. Stop it with 'nodalync stop', or retry once it exits."),
             Self::Ops(_) | Self::Store(_) | Self::Io(_) => None, // too varied
             Self::IdentityNotInitialized => Some("Run 'nodalync init' to create your identity."),
             Self::IdentityExists(_) => Some("Delete the identity directory shown above and run 'nodalync init' again, or use 'nodalync init --wizard' in an interactive terminal."),
@@ -209,12 +220,17 @@ impl CliError {
     }
     /// Format this error as a JSON string for machine consumers (Issue #83).
     ///
-    /// Returns a compact JSON object with error code, message, optional hint,
-    /// and exit code so scripts and MCP integrations can reliably parse errors.
+    /// Returns a compact JSON object with error code, numeric code value,
+    /// severity, whether a retry may succeed, message, optional hint, and
+    /// exit code so scripts and MCP integrations can reliably parse errors.
     pub fn to_json(&self) -> String {
+        let code = self.error_code();
         let json = serde_json::json!({
             "error": {
-                "code": self.error_code().to_string(),
+                "code": code.to_string(),
+                "code_value": code.code(),
+                "severity": code.severity(),
+                "retryable": code.is_retryable(),
                 "message": self.to_string(),
                 "hint": self.suggestion(),
                 "exit_code": self.exit_code(),
@@ -224,6 +240,25 @@ impl CliError {
     }
 }
 
+impl From<&CliError> for ErrorCode {
+    fn from(error: &CliError) -> Self {
+        error.error_code()
+    }
+}
+
+/// The exit code shared by every error with protocol error `code`, if its
+/// category has one.
+fn protocol_exit_code(code: ErrorCode) -> Option<i32> {
+    match code {
+        ErrorCode::NotFound | ErrorCode::VersionNotFound => Some(2),
+        ErrorCode::PaymentRequired | ErrorCode::PaymentInvalid | ErrorCode::InsufficientBalance => {
+            Some(4)
+        }
+        _ if code.is_network_error() => Some(5),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -257,6 +292,36 @@ mod tests {
             "FileNotFound should have a hint"
         );
         assert_eq!(error_obj.get("exit_code").unwrap().as_i64().unwrap(), 2);
+        assert_eq!(error_obj.get("code_value").unwrap().as_u64().unwrap(), 1);
+        assert_eq!(
+            error_obj.get("severity").unwrap().as_str().unwrap(),
+            "warning"
+        );
+        assert!(!error_obj.get("retryable").unwrap().as_bool().unwrap());
+    }
+
+    #[test]
+    fn test_wrapped_errors_keep_protocol_code() {
+        let hash = nodalync_crypto::content_hash(b"missing");
+
+        // Wrapped not-found errors exit like the CLI's own
+        let err = CliError::Ops(nodalync_ops::OpsError::NotFound(hash));
+        assert_eq!(err.error_code(), ErrorCode::NotFound);
+        assert_eq!(err.exit_code(), 2);
+
+        let err = CliError::Store(nodalync_store::StoreError::ContentNotFound(hash));
+        assert_eq!(err.error_code(), ErrorCode::NotFound);
+        assert_eq!(err.exit_code(), 2);
+
+        let err = CliError::Network(nodalync_net::NetworkError::Timeout("30s".to_string()));
+        assert_eq!(err.error_code(), ErrorCode::Timeout);
+        assert_eq!(err.exit_code(), 5);
+
+        // Codes without a shared exit code keep the crate's
+        let err = CliError::Store(nodalync_store::StoreError::schema("missing table"));
+        assert_eq!(err.error_code(), ErrorCode::InternalError);
+        assert_eq!(err.exit_code(), 6);
+        assert_eq!(ErrorCode::from(&err), ErrorCode::InternalError);
     }
 
     /// Regression test for Issue #83: JSON errors with no hint should have null hint.
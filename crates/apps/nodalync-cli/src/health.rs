@@ -0,0 +1,477 @@
+//! Structured node health.
+//!
+//! The node event loop keeps a [`HealthTracker`] up to date with the state of
+//! each component (database, network, DHT, settlement, channels, disk). The
+//! health endpoint serves the resulting [`HealthReport`] as JSON, and the
+//! report is also written to the status file so `nodalync status` can show it.
+//!
+//! Components checked on a timer (database, network, channels, disk) have
+//! their state replaced on every check. Components without a cheap probe
+//! (DHT, settlement) are driven by the outcome of their last operation.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use nodalync_store::ChannelStore;
+use serde::{Deserialize, Serialize};
+
+use crate::context::NodeContext;
+
+/// Free disk space below which the disk is reported as degraded (1 GiB).
+const DISK_DEGRADED_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Free disk space below which the disk is reported as down (100 MiB).
+const DISK_DOWN_BYTES: u64 = 100 * 1024 * 1024;
+
+/// A node component with its own health state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Component {
+    /// Local SQLite database.
+    Db,
+    /// libp2p network connectivity.
+    Network,
+    /// Kademlia DHT operations.
+    Dht,
+    /// On-chain settlement.
+    Settlement,
+    /// Payment channels.
+    Channels,
+    /// Free space in the data directory.
+    Disk,
+}
+
+impl Component {
+    /// All components, in report order.
+    pub const ALL: [Component; 6] = [
+        Component::Db,
+        Component::Network,
+        Component::Dht,
+        Component::Settlement,
+        Component::Channels,
+        Component::Disk,
+    ];
+
+    /// Whether the node cannot serve requests when this component is down.
+    fn is_critical(self) -> bool {
+        matches!(self, Component::Db | Component::Disk)
+    }
+}
+
+impl std::fmt::Display for Component {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Component::Db => "db",
+            Component::Network => "network",
+            Component::Dht => "dht",
+            Component::Settlement => "settlement",
+            Component::Channels => "channels",
+            Component::Disk => "disk",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Health state of a component or of the whole node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthState {
+    /// Working normally.
+    Ok,
+    /// Not configured for this node.
+    Disabled,
+    /// Working with reduced capability.
+    Degraded,
+    /// Not working.
+    Down,
+}
+
+impl std::fmt::Display for HealthState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            HealthState::Ok => "ok",
+            HealthState::Disabled => "disabled",
+            HealthState::Degraded => "degraded",
+            HealthState::Down => "down",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Health of a single component.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentHealth {
+    /// Current state.
+    pub state: HealthState,
+    /// Short human-readable detail (e.g. "12 peers").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Most recent error reported by this component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// Unix timestamp (seconds) of `last_error`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error_at: Option<u64>,
+}
+
+impl ComponentHealth {
+    fn new(state: HealthState, detail: Option<String>) -> Self {
+        Self {
+            state,
+            detail,
+            last_error: None,
+            last_error_at: None,
+        }
+    }
+}
+
+/// The most recent error across all components.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastError {
+    /// Component that reported the error.
+    pub component: Component,
+    /// Error message.
+    pub message: String,
+    /// Unix timestamp (seconds).
+    pub at: u64,
+}
+
+/// Structured health report served by the health endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Overall node state.
+    pub status: HealthState,
+    /// Seconds since the node started.
+    pub uptime_secs: u64,
+    /// Number of connected peers.
+    pub connected_peers: u32,
+    /// Per-component health.
+    pub components: BTreeMap<Component, ComponentHealth>,
+    /// Most recent error across all components.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<LastError>,
+}
+
+impl HealthReport {
+    /// Whether the node can serve requests (readiness).
+    pub fn is_ready(&self) -> bool {
+        self.status != HealthState::Down
+    }
+
+    /// HTTP status code for this report.
+    pub fn http_status(&self) -> u16 {
+        if self.is_ready() {
+            200
+        } else {
+            503
+        }
+    }
+}
+
+/// Shared handle to the health tracker.
+pub type SharedHealth = Arc<HealthTracker>;
+
+/// Tracks component health for a running node.
+#[derive(Debug)]
+pub struct HealthTracker {
+    /// Unix timestamp (seconds) when the node started.
+    start_time: u64,
+    /// Mutable state.
+    inner: Mutex<TrackerState>,
+}
+
+#[derive(Debug)]
+struct TrackerState {
+    components: BTreeMap<Component, ComponentHealth>,
+    connected_peers: u32,
+    last_error: Option<LastError>,
+}
+
+impl HealthTracker {
+    /// Create a tracker with all components initially healthy.
+    pub fn new(start_time: u64) -> Self {
+        let components = Component::ALL
+            .iter()
+            .map(|c| (*c, ComponentHealth::new(HealthState::Ok, None)))
+            .collect();
+        Self {
+            start_time,
+            inner: Mutex::new(TrackerState {
+                components,
+                connected_peers: 0,
+                last_error: None,
+            }),
+        }
+    }
+
+    /// Set a component's state, keeping its last error.
+    pub fn set_state(&self, component: Component, state: HealthState, detail: Option<String>) {
+        let mut inner = self.lock();
+        let entry = inner
+            .components
+            .entry(component)
+            .or_insert_with(|| ComponentHealth::new(state, None));
+        entry.state = state;
+        entry.detail = detail;
+    }
+
+    /// Record a failed operation; the component becomes degraded.
+    pub fn record_error(&self, component: Component, message: impl Into<String>) {
+        let message = message.into();
+        let at = now_secs();
+        let mut inner = self.lock();
+        let entry = inner
+            .components
+            .entry(component)
+            .or_insert_with(|| ComponentHealth::new(HealthState::Degraded, None));
+        if entry.state < HealthState::Degraded {
+            entry.state = HealthState::Degraded;
+        }
+        entry.last_error = Some(message.clone());
+        entry.last_error_at = Some(at);
+        inner.last_error = Some(LastError {
+            component,
+            message,
+            at,
+        });
+    }
+
+    /// Record a successful operation; a degraded component recovers.
+    ///
+    /// The last error is kept for diagnosis.
+    pub fn record_success(&self, component: Component) {
+        let mut inner = self.lock();
+        if let Some(entry) = inner.components.get_mut(&component) {
+            if entry.state == HealthState::Degraded {
+                entry.state = HealthState::Ok;
+            }
+        }
+    }
+
+    /// Build a report of the current state.
+    pub fn report(&self) -> HealthReport {
+        let inner = self.lock();
+        let status = overall_state(&inner.components);
+        HealthReport {
+            status,
+            uptime_secs: now_secs().saturating_sub(self.start_time),
+            connected_peers: inner.connected_peers,
+            components: inner.components.clone(),
+            last_error: inner.last_error.clone(),
+        }
+    }
+
+    /// Probe the timer-checked components.
+    ///
+    /// Called periodically from the event loop, which owns the context.
+    pub fn check(&self, ctx: &NodeContext, base_dir: Option<&Path>) {
+        // Database: a cheap read through the shared connection
+        match ctx.ops.state.settlement.pending_count() {
+            Ok(pending) => self.set_state(
+                Component::Db,
+                HealthState::Ok,
+                Some(format!("{} pending settlements", pending)),
+            ),
+            Err(e) => {
+                self.record_error(Component::Db, e.to_string());
+                self.set_state(Component::Db, HealthState::Down, None);
+            }
+        }
+
+        // Network: reachable peers
+        let peers = ctx.connected_peers() as u32;
+        self.lock().connected_peers = peers;
+        if ctx.network.is_none() {
+            self.set_state(
+                Component::Network,
+                HealthState::Disabled,
+                Some("network not started".to_string()),
+            );
+        } else if peers == 0 {
+            self.set_state(
+                Component::Network,
+                HealthState::Degraded,
+                Some("no connected peers".to_string()),
+            );
+        } else {
+            self.set_state(
+                Component::Network,
+                HealthState::Ok,
+                Some(format!("{} peers", peers)),
+            );
+        }
+
+        // Settlement: only its configuration is checked here; failures are
+        // recorded when batches are submitted
+        if ctx.settlement.is_none() {
+            self.set_state(
+                Component::Settlement,
+                HealthState::Disabled,
+                Some("settlement not configured".to_string()),
+            );
+        }
+
+        // Channels
+        match ctx.ops.state.channels.list_open() {
+            Ok(channels) => self.set_state(
+                Component::Channels,
+                HealthState::Ok,
+                Some(format!("{} open", channels.len())),
+            ),
+            Err(e) => {
+                self.record_error(Component::Channels, e.to_string());
+                self.set_state(Component::Channels, HealthState::Down, None);
+            }
+        }
+
+        // Disk
+        if let Some(dir) = base_dir {
+            match free_disk_space(dir) {
+                Some(free) => {
+                    let state = if free < DISK_DOWN_BYTES {
+                        HealthState::Down
+                    } else if free < DISK_DEGRADED_BYTES {
+                        HealthState::Degraded
+                    } else {
+                        HealthState::Ok
+                    };
+                    self.set_state(
+                        Component::Disk,
+                        state,
+                        Some(format!("{} MiB free", free / (1024 * 1024))),
+                    );
+                }
+                None => self.set_state(
+                    Component::Disk,
+                    HealthState::Disabled,
+                    Some("free space unavailable".to_string()),
+                ),
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TrackerState> {
+        // Health state is plain data; a panic elsewhere doesn't invalidate it
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Combine component states into the node state.
+///
+/// Only critical components (db, disk) take the whole node down; anything
+/// else down or degraded makes the node degraded.
+fn overall_state(components: &BTreeMap<Component, ComponentHealth>) -> HealthState {
+    let mut overall = HealthState::Ok;
+    for (component, health) in components {
+        let state = match health.state {
+            HealthState::Down if component.is_critical() => HealthState::Down,
+            HealthState::Down | HealthState::Degraded => HealthState::Degraded,
+            HealthState::Ok | HealthState::Disabled => HealthState::Ok,
+        };
+        overall = overall.max(state);
+    }
+    overall
+}
+
+/// Free space (bytes) available to this user on the filesystem holding `path`.
+#[cfg(unix)]
+fn free_disk_space(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: c_path is a valid NUL-terminated string and stat is a valid out pointer.
+    let rc = unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) };
+    if rc != 0 {
+        return None;
+    }
+    #[allow(clippy::unnecessary_cast)]
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// Free space (bytes) available on the filesystem holding `path`.
+#[cfg(not(unix))]
+fn free_disk_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_tracker_is_ok() {
+        let tracker = HealthTracker::new(now_secs());
+        let report = tracker.report();
+        assert_eq!(report.status, HealthState::Ok);
+        assert_eq!(report.components.len(), Component::ALL.len());
+        assert!(report.is_ready());
+        assert_eq!(report.http_status(), 200);
+    }
+
+    #[test]
+    fn test_record_error_degrades_and_success_recovers() {
+        let tracker = HealthTracker::new(now_secs());
+
+        tracker.record_error(Component::Settlement, "batch failed");
+        let report = tracker.report();
+        assert_eq!(report.status, HealthState::Degraded);
+        let settlement = &report.components[&Component::Settlement];
+        assert_eq!(settlement.state, HealthState::Degraded);
+        assert_eq!(settlement.last_error.as_deref(), Some("batch failed"));
+        let last = report.last_error.unwrap();
+        assert_eq!(last.component, Component::Settlement);
+
+        tracker.record_success(Component::Settlement);
+        let report = tracker.report();
+        assert_eq!(report.status, HealthState::Ok);
+        // Last error is kept for diagnosis
+        assert!(report.components[&Component::Settlement]
+            .last_error
+            .is_some());
+    }
+
+    #[test]
+    fn test_only_critical_components_take_node_down() {
+        let tracker = HealthTracker::new(now_secs());
+
+        tracker.set_state(Component::Network, HealthState::Down, None);
+        assert_eq!(tracker.report().status, HealthState::Degraded);
+
+        tracker.set_state(Component::Db, HealthState::Down, None);
+        let report = tracker.report();
+        assert_eq!(report.status, HealthState::Down);
+        assert!(!report.is_ready());
+        assert_eq!(report.http_status(), 503);
+    }
+
+    #[test]
+    fn test_report_json_shape() {
+        let tracker = HealthTracker::new(now_secs());
+        tracker.set_state(Component::Settlement, HealthState::Disabled, None);
+
+        let json = serde_json::to_value(tracker.report()).unwrap();
+        assert_eq!(json["status"], "ok");
+        assert_eq!(json["components"]["settlement"]["state"], "disabled");
+        assert!(json["components"]["db"].is_object());
+
+        let parsed: HealthReport = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.components.len(), Component::ALL.len());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_free_disk_space() {
+        let temp = tempfile::TempDir::new().unwrap();
+        assert!(free_disk_space(temp.path()).is_some());
+    }
+}
@@ -0,0 +1,709 @@
+//! Control interface for a running node.
+//!
+//! A node started with `nodalync start` (foreground or `--daemon`) listens on
+//! a local control endpoint: a Unix domain socket at
+//! `<base_dir>/run/node.sock`, or a named pipe on Windows. Other CLI commands detect the running node and
+//! forward their request over this endpoint instead of opening the database
+//! themselves, which avoids SQLite lock conflicts with the daemon and reuses
+//! its already-bootstrapped network connection.
+//!
+//! The protocol is one newline-delimited JSON request per connection,
+//! answered by one newline-delimited JSON response. Requests are executed
+//! on the node's event loop, one at a time.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use nodalync_types::Visibility;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{mpsc, oneshot, watch};
+use tracing::{debug, info, warn};
+
+use crate::commands;
+use crate::context::NodeContext;
+use crate::error::{CliError, CliResult};
+use crate::node_runner::check_existing_node;
+use crate::output::OutputFormat;
+
+/// Control socket file name (Unix).
+#[cfg(unix)]
+const SOCKET_FILE_NAME: &str = "node.sock";
+
+/// Directory holding the control socket (Unix), private to its owner.
+#[cfg(unix)]
+const SOCKET_DIR_NAME: &str = "run";
+
+/// Maximum size of a single control request.
+const MAX_REQUEST_BYTES: u64 = 64 * 1024;
+
+/// Maximum number of requests waiting for the event loop.
+const REQUEST_QUEUE_SIZE: usize = 16;
+
+/// How long a client waits for the node to answer.
+///
+/// Generous because queries and channel operations involve network round trips.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(300);
+
+// =============================================================================
+// Protocol
+// =============================================================================
+
+/// An operation requested over the control interface.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum IpcRequest {
+    /// Node status.
+    Status,
+    /// Publish a local file. The path must be absolute.
+    Publish {
+        file: PathBuf,
+        price: Option<f64>,
+        visibility: Visibility,
+        title: Option<String>,
+        description: Option<String>,
+        #[serde(default)]
+        publish_at: Option<u64>,
+        #[serde(default)]
+        schema: Option<String>,
+        #[serde(default)]
+        fields: Option<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+    },
+    /// Preview content metadata.
+    Preview { hash: String },
+    /// Query content. The output path, if any, must be absolute.
+    Query {
+        hash: String,
+        output: Option<PathBuf>,
+        #[serde(default)]
+        capability: Option<String>,
+        #[serde(default)]
+        section: Option<u32>,
+        #[serde(default)]
+        metered: Option<commands::query::MeteredRead>,
+        #[serde(default)]
+        trial: bool,
+        #[serde(default)]
+        referrer: Option<String>,
+    },
+    /// Open a payment channel.
+    OpenChannel { peer_id: String, deposit: f64 },
+    /// Close a payment channel.
+    CloseChannel { peer_id: String },
+    /// List payment channels.
+    ListChannels,
+    /// Plan (and unless `dry_run`, perform) a channel rebalance.
+    RebalanceChannels { dry_run: bool },
+    /// Force settlement of pending payments.
+    Settle,
+    /// Integrity report, optionally after a content scrub.
+    Doctor { scrub: bool },
+    /// Version history, after fetching missing versions from the owner.
+    RepairVersions { hash: String },
+}
+
+/// A request together with the caller's output format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IpcEnvelope {
+    /// Format the output should be rendered in.
+    pub format: OutputFormat,
+    /// The requested operation.
+    #[serde(flatten)]
+    pub request: IpcRequest,
+}
+
+/// Response to a control request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum IpcResponse {
+    /// The command succeeded; `output` is the rendered command output.
+    Ok { output: String },
+    /// The command failed.
+    Error { message: String },
+}
+
+impl IpcResponse {
+    /// Create an error response.
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::Error {
+            message: message.into(),
+        }
+    }
+
+    /// Convert into a command result.
+    pub fn into_result(self) -> CliResult<String> {
+        match self {
+            Self::Ok { output } => Ok(output),
+            Self::Error { message } => Err(CliError::User(message)),
+        }
+    }
+}
+
+impl From<CliResult<String>> for IpcResponse {
+    fn from(result: CliResult<String>) -> Self {
+        match result {
+            Ok(output) => Self::Ok { output },
+            Err(e) => Self::error(e.to_string()),
+        }
+    }
+}
+
+// =============================================================================
+// Server
+// =============================================================================
+
+/// A control request waiting to be executed by the node event loop.
+pub struct IpcCall {
+    envelope: IpcEnvelope,
+    reply: oneshot::Sender<IpcResponse>,
+}
+
+impl IpcCall {
+    /// Execute the request against the node context and send the response.
+    pub async fn handle(self, ctx: &mut NodeContext) {
+        debug!(request = ?self.envelope.request, "Handling control request");
+        let response = dispatch(ctx, self.envelope).await;
+        // The client may have given up waiting; nothing to do then.
+        let _ = self.reply.send(response);
+    }
+}
+
+/// Run a request against the node context.
+async fn dispatch(ctx: &mut NodeContext, envelope: IpcEnvelope) -> IpcResponse {
+    let format = envelope.format;
+    let result = match envelope.request {
+        IpcRequest::Status => commands::status::status_with_context(ctx, format),
+        IpcRequest::Publish {
+            file,
+            price,
+            visibility,
+            title,
+            description,
+            publish_at,
+            schema,
+            fields,
+            tags,
+        } => {
+            commands::publish::publish_with_context(
+                ctx,
+                format,
+                &file,
+                price,
+                visibility,
+                title,
+                description,
+                publish_at,
+                schema,
+                fields,
+                tags,
+            )
+            .await
+        }
+        IpcRequest::Preview { hash } => {
+            commands::preview::preview_with_context(ctx, format, &hash).await
+        }
+        IpcRequest::Query {
+            hash,
+            output,
+            capability,
+            section,
+            metered,
+            trial,
+            referrer,
+        } => {
+            commands::query::query_with_context(
+                ctx, format, &hash, output, capability, section, metered, trial, referrer,
+            )
+            .await
+        }
+        IpcRequest::OpenChannel { peer_id, deposit } => {
+            commands::channel::open_channel_with_context(ctx, format, &peer_id, deposit).await
+        }
+        IpcRequest::CloseChannel { peer_id } => {
+            commands::channel::close_channel_with_context(ctx, format, &peer_id).await
+        }
+        IpcRequest::ListChannels => commands::channel::list_channels_with_context(ctx, format),
+        IpcRequest::RebalanceChannels { dry_run } => {
+            commands::channel::rebalance_channels_with_context(ctx, format, dry_run).await
+        }
+        IpcRequest::Settle => commands::settle::settle_with_context(ctx, format).await,
+        IpcRequest::Doctor { scrub } => {
+            commands::doctor::doctor_with_context(ctx, format, scrub).await
+        }
+        IpcRequest::RepairVersions { hash } => {
+            commands::versions::versions_with_context(ctx, format, &hash, true).await
+        }
+    };
+    IpcResponse::from(result)
+}
+
+/// Get the control socket path for the given base directory.
+#[cfg(unix)]
+pub fn socket_path(base_dir: &Path) -> PathBuf {
+    base_dir.join(SOCKET_DIR_NAME).join(SOCKET_FILE_NAME)
+}
+
+/// Create the control socket's directory, or check an existing one, so that
+/// only we can reach the socket inside it.
+///
+/// The socket is created with umask permissions before they can be
+/// narrowed, so the directory is what keeps other users out.
+#[cfg(unix)]
+fn create_private_dir(dir: &Path) -> CliResult<()> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+    match std::fs::DirBuilder::new().mode(0o700).create(dir) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(e.into()),
+    }
+    let metadata = std::fs::symlink_metadata(dir)?;
+    if !metadata.is_dir() || metadata.uid() != effective_uid() {
+        return Err(CliError::config(format!(
+            "Control socket directory {} is not a directory owned by this user",
+            dir.display()
+        )));
+    }
+    std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    Ok(())
+}
+
+/// The user this process runs as.
+#[cfg(unix)]
+fn effective_uid() -> u32 {
+    // SAFETY: geteuid has no preconditions and cannot fail.
+    unsafe { libc::geteuid() }
+}
+
+/// Whether a control connection comes from the user running the node.
+#[cfg(unix)]
+fn peer_is_owner(stream: &tokio::net::UnixStream) -> bool {
+    stream
+        .peer_cred()
+        .is_ok_and(|cred| cred.uid() == effective_uid())
+}
+
+/// Get the control pipe name for the given base directory.
+///
+/// Pipe names are global, so the base directory is folded into the name to
+/// keep nodes with different data directories apart.
+#[cfg(windows)]
+pub fn pipe_name(base_dir: &Path) -> String {
+    let suffix: String = base_dir
+        .display()
+        .to_string()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!(r"\\.\pipe\nodalync-{}", suffix)
+}
+
+/// Start listening for control requests.
+///
+/// Accepted requests are delivered on the returned channel and must be
+/// executed by the caller (the node event loop) via [`IpcCall::handle`].
+/// The listener stops when `shutdown_rx` changes to true.
+#[cfg(unix)]
+pub fn spawn_ipc_server(
+    base_dir: &Path,
+    mut shutdown_rx: watch::Receiver<bool>,
+) -> CliResult<mpsc::Receiver<IpcCall>> {
+    use std::os::unix::fs::PermissionsExt;
+    use tokio::net::UnixListener;
+
+    let path = socket_path(base_dir);
+    if let Some(dir) = path.parent() {
+        create_private_dir(dir)?;
+    }
+
+    // A socket left behind by a crashed node would make bind fail. The caller
+    // has already checked that no other node is running.
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+
+    let listener = UnixListener::bind(&path).map_err(|e| {
+        CliError::config(format!(
+            "Failed to bind control socket {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    // Only the owner may control the node
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+
+    info!("Control socket listening on {}", path.display());
+
+    let (tx, rx) = mpsc::channel(REQUEST_QUEUE_SIZE);
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                result = shutdown_rx.changed() => {
+                    if result.is_err() || *shutdown_rx.borrow() {
+                        break;
+                    }
+                }
+                accepted = listener.accept() => match accepted {
+                    // Only the owner may control the node, whatever the
+                    // file permissions say
+                    Ok((stream, _)) if !peer_is_owner(&stream) => {
+                        warn!("Refused a control connection from another user");
+                    }
+                    Ok((stream, _)) => {
+                        tokio::spawn(handle_connection(stream, tx.clone()));
+                    }
+                    Err(e) => warn!("Control socket accept error: {}", e),
+                }
+            }
+        }
+        let _ = std::fs::remove_file(&path);
+        debug!("Control socket closed");
+    });
+
+    Ok(rx)
+}
+
+/// Start listening for control requests.
+///
+/// Accepted requests are delivered on the returned channel and must be
+/// executed by the caller (the node event loop) via [`IpcCall::handle`].
+/// The listener stops when `shutdown_rx` changes to true.
+#[cfg(windows)]
+pub fn spawn_ipc_server(
+    base_dir: &Path,
+    mut shutdown_rx: watch::Receiver<bool>,
+) -> CliResult<mpsc::Receiver<IpcCall>> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let name = pipe_name(base_dir);
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(&name)
+        .map_err(|e| CliError::config(format!("Failed to create control pipe {}: {}", name, e)))?;
+
+    info!("Control pipe listening on {}", name);
+
+    let (tx, rx) = mpsc::channel(REQUEST_QUEUE_SIZE);
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                result = shutdown_rx.changed() => {
+                    if result.is_err() || *shutdown_rx.borrow() {
+                        break;
+                    }
+                }
+                connected = server.connect() => {
+                    if let Err(e) = connected {
+                        warn!("Control pipe connect error: {}", e);
+                        continue;
+                    }
+                    // Create the next instance before handing off the connected one
+                    let next = match ServerOptions::new().create(&name) {
+                        Ok(next) => next,
+                        Err(e) => {
+                            warn!("Failed to create control pipe instance: {}", e);
+                            break;
+                        }
+                    };
+                    let stream = std::mem::replace(&mut server, next);
+                    tokio::spawn(handle_connection(stream, tx.clone()));
+                }
+            }
+        }
+        debug!("Control pipe closed");
+    });
+
+    Ok(rx)
+}
+
+/// Start listening for control requests (unsupported platform stub).
+#[cfg(not(any(unix, windows)))]
+pub fn spawn_ipc_server(
+    _base_dir: &Path,
+    _shutdown_rx: watch::Receiver<bool>,
+) -> CliResult<mpsc::Receiver<IpcCall>> {
+    Err(CliError::config(
+        "Control interface is not supported on this platform",
+    ))
+}
+
+/// Remove the control endpoint left behind by a stopped node.
+///
+/// Named pipes disappear with their last handle, so this only affects the
+/// Unix socket file.
+pub fn remove_ipc_endpoint(base_dir: &Path) {
+    #[cfg(unix)]
+    {
+        let _ = std::fs::remove_file(socket_path(base_dir));
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = base_dir;
+    }
+}
+
+/// Wait for the next control request.
+///
+/// Never resolves when the control interface is disabled, so it can be used
+/// unconditionally as a `select!` branch.
+pub async fn next_ipc_call(rx: &mut Option<mpsc::Receiver<IpcCall>>) -> Option<IpcCall> {
+    match rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Serve a single control connection.
+async fn handle_connection<S>(stream: S, tx: mpsc::Sender<IpcCall>)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut reader = BufReader::new(reader).take(MAX_REQUEST_BYTES);
+
+    let mut line = String::new();
+    let response = match reader.read_line(&mut line).await {
+        Ok(0) => return,
+        Ok(_) => match serde_json::from_str::<IpcEnvelope>(&line) {
+            Ok(envelope) => forward(envelope, &tx).await,
+            Err(e) => IpcResponse::error(format!("Invalid control request: {}", e)),
+        },
+        Err(e) => {
+            debug!("Failed to read control request: {}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = write_message(&mut writer, &response).await {
+        debug!("Failed to write control response: {}", e);
+    }
+}
+
+/// Hand a request to the event loop and wait for its response.
+async fn forward(envelope: IpcEnvelope, tx: &mpsc::Sender<IpcCall>) -> IpcResponse {
+    let (reply, reply_rx) = oneshot::channel();
+    if tx.send(IpcCall { envelope, reply }).await.is_err() {
+        return IpcResponse::error("Node is shutting down");
+    }
+    reply_rx
+        .await
+        .unwrap_or_else(|_| IpcResponse::error("Node is shutting down"))
+}
+
+/// Write a message as a single JSON line.
+async fn write_message<W, T>(writer: &mut W, message: &T) -> CliResult<()>
+where
+    W: AsyncWrite + Unpin,
+    T: Serialize,
+{
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+// =============================================================================
+// Client
+// =============================================================================
+
+/// Forward a request to the running node, if there is one.
+///
+/// Returns `Ok(None)` when no node is running or it is not accepting control
+/// connections, so the caller can fall back to opening the database itself.
+/// Errors reported by the node are returned as `Err`.
+pub async fn try_daemon_request(
+    base_dir: &Path,
+    format: OutputFormat,
+    request: IpcRequest,
+) -> CliResult<Option<String>> {
+    if check_existing_node(base_dir).is_none() {
+        return Ok(None);
+    }
+
+    let envelope = IpcEnvelope { format, request };
+    let response = match connect_and_send(base_dir, &envelope).await {
+        Ok(response) => response,
+        Err(ConnectError::Unavailable(e)) => {
+            debug!("Running node has no control interface: {}", e);
+            return Ok(None);
+        }
+        Err(ConnectError::Failed(e)) => return Err(e),
+    };
+
+    response.into_result().map(Some)
+}
+
+/// Resolve a path against the current directory.
+///
+/// The daemon runs in its own working directory, so paths sent to it must
+/// be absolute.
+pub fn absolute_path(path: &Path) -> CliResult<PathBuf> {
+    if path.is_absolute() {
+        Ok(path.to_path_buf())
+    } else {
+        Ok(std::env::current_dir()?.join(path))
+    }
+}
+
+/// Failure modes when talking to the running node.
+enum ConnectError {
+    /// Could not connect; the caller should fall back to local access.
+    Unavailable(std::io::Error),
+    /// Connected, but the exchange failed.
+    Failed(CliError),
+}
+
+#[cfg(unix)]
+async fn connect_and_send(
+    base_dir: &Path,
+    envelope: &IpcEnvelope,
+) -> Result<IpcResponse, ConnectError> {
+    let stream = tokio::net::UnixStream::connect(socket_path(base_dir))
+        .await
+        .map_err(ConnectError::Unavailable)?;
+    exchange(stream, envelope)
+        .await
+        .map_err(ConnectError::Failed)
+}
+
+#[cfg(windows)]
+async fn connect_and_send(
+    base_dir: &Path,
+    envelope: &IpcEnvelope,
+) -> Result<IpcResponse, ConnectError> {
+    let stream = tokio::net::windows::named_pipe::ClientOptions::new()
+        .open(pipe_name(base_dir))
+        .map_err(ConnectError::Unavailable)?;
+    exchange(stream, envelope)
+        .await
+        .map_err(ConnectError::Failed)
+}
+
+#[cfg(not(any(unix, windows)))]
+async fn connect_and_send(
+    _base_dir: &Path,
+    _envelope: &IpcEnvelope,
+) -> Result<IpcResponse, ConnectError> {
+    Err(ConnectError::Unavailable(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "control interface is not supported on this platform",
+    )))
+}
+
+/// Send a request and read the response.
+async fn exchange<S>(stream: S, envelope: &IpcEnvelope) -> CliResult<IpcResponse>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    write_message(&mut writer, envelope).await?;
+
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    tokio::time::timeout(RESPONSE_TIMEOUT, reader.read_line(&mut line))
+        .await
+        .map_err(|_| CliError::user("Timed out waiting for the running node to respond"))??;
+
+    if line.is_empty() {
+        return Err(CliError::user(
+            "Running node closed the control connection without responding",
+        ));
+    }
+    Ok(serde_json::from_str(&line)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_roundtrip() {
+        let envelope = IpcEnvelope {
+            format: OutputFormat::Json,
+            request: IpcRequest::Query {
+                hash: "abc".to_string(),
+                output: Some(PathBuf::from("/tmp/out")),
+                capability: None,
+                section: Some(1),
+                metered: None,
+                trial: false,
+                referrer: None,
+            },
+        };
+
+        let json = serde_json::to_string(&envelope).unwrap();
+        assert!(json.contains("\"command\":\"query\""));
+        assert!(json.contains("\"format\":\"json\""));
+
+        let parsed: IpcEnvelope = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, envelope);
+    }
+
+    #[test]
+    fn test_response_into_result() {
+        let ok = IpcResponse::Ok {
+            output: "done".to_string(),
+        };
+        assert_eq!(ok.into_result().unwrap(), "done");
+
+        let err = IpcResponse::error("boom");
+        assert!(matches!(err.into_result(), Err(CliError::User(m)) if m == "boom"));
+    }
+
+    #[test]
+    fn test_absolute_path() {
+        let abs = absolute_path(Path::new("relative/file.txt")).unwrap();
+        assert!(abs.is_absolute());
+        assert!(abs.ends_with("relative/file.txt"));
+    }
+
+    #[tokio::test]
+    async fn test_no_daemon_falls_back() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let result = try_daemon_request(temp_dir.path(), OutputFormat::Human, IpcRequest::Status)
+            .await
+            .unwrap();
+        assert!(result.is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_socket_roundtrip() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
+        // A socket directory left open to others is narrowed to the owner
+        let dir = temp_dir.path().join(SOCKET_DIR_NAME);
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let mut rx = spawn_ipc_server(temp_dir.path(), shutdown_rx).unwrap();
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&dir), 0o700);
+        assert_eq!(mode(&socket_path(temp_dir.path())), 0o600);
+
+        // Stand-in for the event loop: echo the command name back
+        tokio::spawn(async move {
+            while let Some(call) = rx.recv().await {
+                let output = format!("{:?}", call.envelope.request);
+                let _ = call.reply.send(IpcResponse::Ok { output });
+            }
+        });
+
+        let envelope = IpcEnvelope {
+            format: OutputFormat::Human,
+            request: IpcRequest::Settle,
+        };
+        let stream = tokio::net::UnixStream::connect(socket_path(temp_dir.path()))
+            .await
+            .unwrap();
+        let response = exchange(stream, &envelope).await.unwrap();
+        assert_eq!(response.into_result().unwrap(), "Settle");
+
+        shutdown_tx.send(true).unwrap();
+    }
+}
@@ -0,0 +1,357 @@
+//! IPFS interop for importing CIDs as L0 content.
+//!
+//! Content is fetched through an IPFS node's RPC API when one is configured,
+//! since the node checks every block against its CID. Through a gateway,
+//! only raw-codec SHA-256 CIDs (a single block) can be checked here; other
+//! CIDs are trusted to the gateway and reported as unverified.
+//!
+//! Pinning back adds a small JSON record linking the Nodalync hash to the
+//! source CID to the IPFS node, pinned, so IPFS users can find the content's
+//! provenance and payment rails.
+
+use std::fmt;
+use std::time::Duration;
+
+use nodalync_crypto::Hash;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::config::IpfsConfig;
+
+/// Multicodec code of raw binary blocks.
+const CODEC_RAW: u64 = 0x55;
+
+/// Multicodec code of dag-pb (UnixFS) blocks.
+const CODEC_DAG_PB: u64 = 0x70;
+
+/// Multihash code of SHA-256.
+const MULTIHASH_SHA2_256: u64 = 0x12;
+
+/// Boundary of multipart bodies sent to the node API.
+const MULTIPART_BOUNDARY: &str = "nodalync-ipfs-import";
+
+/// A parsed content identifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cid {
+    /// The CID as given.
+    text: String,
+    /// CID version (0 or 1).
+    pub version: u64,
+    /// Multicodec of the block.
+    pub codec: u64,
+    /// Multihash function code.
+    pub hash_code: u64,
+    /// Multihash digest.
+    pub digest: Vec<u8>,
+}
+
+impl Cid {
+    /// Parse a CIDv0 (`Qm...`) or a base32 CIDv1 (`b...`).
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let bytes = if s.len() == 46 && s.starts_with("Qm") {
+            let bytes = bs58::decode(s).into_vec().map_err(|e| e.to_string())?;
+            // A CIDv0 is a bare SHA-256 multihash of a dag-pb block
+            let mut cid = vec![0, CODEC_DAG_PB as u8];
+            cid.extend_from_slice(&bytes);
+            cid
+        } else if let Some(rest) = s.strip_prefix('b') {
+            decode_base32(rest).ok_or("invalid base32")?
+        } else {
+            return Err("expected a CIDv0 (Qm...) or base32 CIDv1 (b...)".to_string());
+        };
+
+        let mut input = bytes.as_slice();
+        let version = read_varint(&mut input).ok_or("truncated CID")?;
+        if version > 1 {
+            return Err(format!("unsupported CID version {}", version));
+        }
+        let codec = read_varint(&mut input).ok_or("truncated CID")?;
+        let hash_code = read_varint(&mut input).ok_or("truncated CID")?;
+        let len = read_varint(&mut input).ok_or("truncated CID")?;
+        if input.len() as u64 != len {
+            return Err("multihash length mismatch".to_string());
+        }
+
+        Ok(Self {
+            text: s.to_string(),
+            version,
+            codec,
+            hash_code,
+            digest: input.to_vec(),
+        })
+    }
+
+    /// Check `data` against the CID, or `None` if it can't be checked
+    /// without the block structure.
+    pub fn verify(&self, data: &[u8]) -> Option<bool> {
+        if self.codec != CODEC_RAW || self.hash_code != MULTIHASH_SHA2_256 {
+            return None;
+        }
+        Some(Sha256::digest(data).as_slice() == self.digest.as_slice())
+    }
+}
+
+impl fmt::Display for Cid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Content fetched from IPFS.
+#[derive(Debug, Clone)]
+pub struct Fetched {
+    /// Raw content.
+    pub data: Vec<u8>,
+    /// Where it was fetched from.
+    pub source: String,
+    /// Whether the content was checked against the CID.
+    pub verified: bool,
+    /// Content type reported by a gateway.
+    pub content_type: Option<String>,
+}
+
+/// Client for an IPFS gateway and, optionally, a node's RPC API.
+pub struct IpfsClient {
+    client: reqwest::Client,
+    gateway: String,
+    api: Option<String>,
+    max_bytes: usize,
+}
+
+impl IpfsClient {
+    /// Create a client from the `[ipfs]` configuration.
+    pub fn new(config: &IpfsConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs.max(1)))
+            .user_agent(concat!("nodalync/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            gateway: config.gateway.trim_end_matches('/').to_string(),
+            api: config
+                .api
+                .as_ref()
+                .map(|api| api.trim_end_matches('/').to_string()),
+            max_bytes: (config.max_import_mb as usize).saturating_mul(1024 * 1024),
+        }
+    }
+
+    /// Whether a node API is configured.
+    pub fn has_api(&self) -> bool {
+        self.api.is_some()
+    }
+
+    /// Fetch a CID's content.
+    ///
+    /// Fails if the content is too large or, when it can be checked,
+    /// doesn't match the CID.
+    pub async fn fetch(&self, cid: &Cid) -> Result<Fetched, String> {
+        let (request, source) = match self.api {
+            Some(ref api) => (
+                self.client
+                    .post(format!("{}/api/v0/cat", api))
+                    .query(&[("arg", cid.to_string())]),
+                api.clone(),
+            ),
+            None => (
+                self.client.get(format!("{}/ipfs/{}", self.gateway, cid)),
+                self.gateway.clone(),
+            ),
+        };
+
+        let mut response = request.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("{} returned status {}", source, response.status()));
+        }
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.split(';').next().unwrap_or(v).trim().to_string())
+            .filter(|_| self.api.is_none());
+
+        let mut data = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+            if data.len() + chunk.len() > self.max_bytes {
+                return Err(format!(
+                    "content larger than {} MB (max_import_mb)",
+                    self.max_bytes / (1024 * 1024)
+                ));
+            }
+            data.extend_from_slice(&chunk);
+        }
+
+        let verified = match cid.verify(&data) {
+            Some(false) => return Err(format!("content from {} does not match {}", source, cid)),
+            Some(true) => true,
+            // The node checks blocks as it reads them
+            None => self.api.is_some(),
+        };
+
+        Ok(Fetched {
+            data,
+            source,
+            verified,
+            content_type,
+        })
+    }
+
+    /// Add `data` to the node, pinned, returning its CID.
+    pub async fn add_pinned(&self, name: &str, data: &[u8]) -> Result<String, String> {
+        let api = self
+            .api
+            .as_ref()
+            .ok_or("no IPFS node API configured (api under [ipfs])")?;
+
+        #[derive(Deserialize)]
+        struct AddResponse {
+            #[serde(rename = "Hash")]
+            hash: String,
+        }
+
+        let response = self
+            .client
+            .post(format!("{}/api/v0/add", api))
+            .query(&[("pin", "true"), ("cid-version", "1")])
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={}", MULTIPART_BOUNDARY),
+            )
+            .body(multipart_file(name, data))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("{} returned status {}", api, response.status()));
+        }
+
+        let added: AddResponse = response.json().await.map_err(|e| e.to_string())?;
+        Ok(added.hash)
+    }
+}
+
+/// The record pinned back to IPFS: the Nodalync hash of a CID's content.
+pub fn pointer_record(hash: &Hash, cid: &Cid) -> Vec<u8> {
+    serde_json::to_vec(&serde_json::json!({
+        "nodalync": hash.to_string(),
+        "cid": cid.to_string(),
+    }))
+    .unwrap_or_default()
+}
+
+/// File extension publish maps back to `content_type`.
+pub fn extension_for(content_type: Option<&str>) -> &'static str {
+    match content_type {
+        Some("text/plain") => "txt",
+        Some("text/markdown") => "md",
+        Some("text/html") => "html",
+        Some("application/json") => "json",
+        Some("application/pdf") => "pdf",
+        Some("image/png") => "png",
+        Some("image/jpeg") => "jpg",
+        _ => "bin",
+    }
+}
+
+/// A multipart body holding one file field.
+fn multipart_file(name: &str, data: &[u8]) -> Vec<u8> {
+    let mut body = format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\n\
+         Content-Type: application/octet-stream\r\n\r\n",
+        MULTIPART_BOUNDARY, name
+    )
+    .into_bytes();
+    body.extend_from_slice(data);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", MULTIPART_BOUNDARY).as_bytes());
+    body
+}
+
+/// Read an unsigned varint.
+fn read_varint(input: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for (i, byte) in input.iter().enumerate().take(9) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            *input = &input[i + 1..];
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Decode unpadded lowercase RFC 4648 base32.
+fn decode_base32(s: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(s.len() * 5 / 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in s.bytes() {
+        let value = match c {
+            b'a'..=b'z' => c - b'a',
+            b'2'..=b'7' => c - b'2' + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | u32::from(value);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// CIDv1 (raw, SHA-256) of "hello world".
+    const HELLO_RAW: &str = "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e";
+
+    #[test]
+    fn test_parse_cid() {
+        let cid = Cid::parse(HELLO_RAW).unwrap();
+        assert_eq!(cid.version, 1);
+        assert_eq!(cid.codec, CODEC_RAW);
+        assert_eq!(cid.hash_code, MULTIHASH_SHA2_256);
+        assert_eq!(cid.digest.len(), 32);
+        assert_eq!(cid.to_string(), HELLO_RAW);
+
+        let cid = Cid::parse("QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o").unwrap();
+        assert_eq!(cid.version, 0);
+        assert_eq!(cid.codec, CODEC_DAG_PB);
+        assert_eq!(cid.digest.len(), 32);
+
+        for bad in ["", "zdj7W", "bafk!", "Qm123", "bafkrei"] {
+            assert!(Cid::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let cid = Cid::parse(HELLO_RAW).unwrap();
+        assert_eq!(cid.verify(b"hello world"), Some(true));
+        assert_eq!(cid.verify(b"hello there"), Some(false));
+
+        let cid = Cid::parse("QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o").unwrap();
+        assert_eq!(cid.verify(b"hello world"), None);
+    }
+
+    #[test]
+    fn test_pointer_record() {
+        let cid = Cid::parse(HELLO_RAW).unwrap();
+        let hash = nodalync_crypto::content_hash(b"hello world");
+        let record: serde_json::Value =
+            serde_json::from_slice(&pointer_record(&hash, &cid)).unwrap();
+        assert_eq!(record["nodalync"], hash.to_string());
+        assert_eq!(record["cid"], HELLO_RAW);
+    }
+
+    #[test]
+    fn test_extension_roundtrip() {
+        assert_eq!(extension_for(Some("text/markdown")), "md");
+        assert_eq!(extension_for(Some("application/x-tar")), "bin");
+        assert_eq!(extension_for(None), "bin");
+    }
+}
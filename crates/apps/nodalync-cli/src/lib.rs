@@ -10,6 +10,10 @@
 //! - **Economics**: Check balance, deposit, withdraw, settle
 //! - **Node Management**: Start, status, stop the node
 //!
+//! While a node is running, commands that need node state (status, publish,
+//! query, channels, settle) are forwarded to it over its control socket
+//! (see [`ipc`]) instead of opening the database directly.
+//!
 //! # Quick Start
 //!
 //! ```bash
@@ -38,21 +42,28 @@
 //! Configuration is loaded from `~/.nodalync/config.toml`. Override with `--config`.
 
 pub mod alerting;
+pub mod bridge;
 pub mod cli;
 pub mod commands;
 pub mod config;
 pub mod context;
 pub mod error;
+pub mod health;
+pub mod ipc;
+pub mod ipfs;
+pub mod logging;
 pub mod metrics;
 pub mod node_runner;
+pub mod notifications;
 pub mod output;
 pub mod progress;
 pub mod prompt;
 pub mod signals;
+pub mod webhooks;
 pub mod wizard;
 
 // Re-export main types
-pub use cli::{Cli, Commands, CompletionShell, OutputFormatArg, VisibilityArg};
+pub use cli::{Cli, Commands, CompletionShell, OutputFormatArg, RetentionCommands, VisibilityArg};
 pub use config::CliConfig;
 pub use context::NodeContext;
 pub use error::{CliError, CliResult};
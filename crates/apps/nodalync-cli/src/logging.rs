@@ -0,0 +1,605 @@
+//! Logging setup and log file access.
+//!
+//! Diagnostics go to two places:
+//!
+//! - **stderr**, human-readable, when `--verbose` or `RUST_LOG` is set.
+//! - **Log files** under `<base_dir>/logs/`, one JSON object per line, at the
+//!   levels configured in the `[logging]` section. The current file is
+//!   `nodalync.log`; when it reaches `max_file_size_mb` it is renamed to
+//!   `nodalync.log.1` (older files shift up) and a new one is started.
+//!
+//! - **An OTLP collector**, as traces, when `[logging.otlp]` names an
+//!   endpoint. Spans carry the request IDs of the queries they belong to,
+//!   so a query can be followed from requester to provider.
+//!
+//! `nodalync logs` reads the files back with [`read_entries`] and
+//! [`LogFollower`].
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use nodalync_crypto::current_timestamp;
+use serde::{Deserialize, Serialize};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, EnvFilter};
+
+use crate::config::LoggingConfig;
+use crate::error::{CliError, CliResult};
+
+/// Directory (under the base directory) holding log files.
+pub const LOG_DIR: &str = "logs";
+
+/// Name of the current log file.
+pub const LOG_FILE: &str = "nodalync.log";
+
+/// Get the log directory for a base directory.
+pub fn log_dir(base_dir: &Path) -> PathBuf {
+    base_dir.join(LOG_DIR)
+}
+
+/// Get the current log file path for a base directory.
+pub fn log_file_path(base_dir: &Path) -> PathBuf {
+    log_dir(base_dir).join(LOG_FILE)
+}
+
+/// Path of the `n`th rotated file (`nodalync.log.n`).
+fn rotated_path(path: &Path, n: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Tracer provider exporting over OTLP, kept to flush it on exit.
+#[cfg(feature = "otlp")]
+static TRACER_PROVIDER: std::sync::OnceLock<opentelemetry_sdk::trace::SdkTracerProvider> =
+    std::sync::OnceLock::new();
+
+/// Initialize logging.
+///
+/// The stderr layer keeps the previous behaviour (only with `--verbose` or
+/// `RUST_LOG`). The file layer is added when `base_dir` is given and file
+/// logging is enabled in the config, and the OTLP layer when an endpoint is
+/// configured. Export runs on a background thread, so a daemon initializes
+/// logging after forking.
+pub fn init(config: &LoggingConfig, base_dir: Option<&Path>, verbose: bool) -> CliResult<()> {
+    let stderr_layer = if verbose || std::env::var("RUST_LOG").is_ok() {
+        let filter = if verbose {
+            EnvFilter::from_default_env().add_directive("nodalync=debug".parse().unwrap())
+        } else {
+            EnvFilter::from_default_env()
+        };
+        Some(
+            fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_filter(filter),
+        )
+    } else {
+        None
+    };
+
+    let file_layer = match base_dir {
+        Some(dir) if config.file => {
+            let filter = EnvFilter::try_new(config.filter_directives()).map_err(|e| {
+                CliError::config(format!("Invalid [logging] level configuration: {}", e))
+            })?;
+            let file = RotatingFile::open(
+                &log_file_path(dir),
+                config.max_file_size_mb.saturating_mul(1024 * 1024),
+                config.max_files,
+            )?;
+            Some(JsonFileLayer::new(file).with_filter(filter))
+        }
+        _ => None,
+    };
+
+    let subscriber = tracing_subscriber::registry()
+        .with(stderr_layer)
+        .with(file_layer);
+
+    #[cfg(feature = "otlp")]
+    let subscriber = subscriber.with(match &config.otlp.endpoint {
+        Some(endpoint) => {
+            let filter = EnvFilter::try_new(&config.otlp.level)
+                .map_err(|e| CliError::config(format!("Invalid [logging.otlp] level: {}", e)))?;
+            let tracer = otlp_tracer(&config.otlp, endpoint)?;
+            Some(
+                tracing_opentelemetry::layer()
+                    .with_tracer(tracer)
+                    .with_filter(filter),
+            )
+        }
+        None => None,
+    });
+    #[cfg(not(feature = "otlp"))]
+    if config.otlp.endpoint.is_some() {
+        return Err(CliError::config(
+            "[logging.otlp] needs a build with the `otlp` feature",
+        ));
+    }
+
+    // Ignore the error if a global subscriber is already set (e.g. in tests)
+    let _ = subscriber.try_init();
+    Ok(())
+}
+
+/// Flush pending traces and stop exporting. Does nothing without export.
+pub fn shutdown() {
+    #[cfg(feature = "otlp")]
+    if let Some(provider) = TRACER_PROVIDER.get() {
+        let _ = provider.shutdown();
+    }
+}
+
+/// Start exporting spans to `endpoint`, returning the tracer to hand to
+/// the tracing layer.
+#[cfg(feature = "otlp")]
+fn otlp_tracer(
+    config: &crate::config::OtlpConfig,
+    endpoint: &str,
+) -> CliResult<opentelemetry_sdk::trace::Tracer> {
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_otlp::WithExportConfig;
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(endpoint)
+        .build()
+        .map_err(|e| CliError::config(format!("Invalid [logging.otlp] endpoint: {}", e)))?;
+    let resource = opentelemetry_sdk::Resource::builder()
+        .with_service_name(config.service_name.clone())
+        .build();
+    let provider = opentelemetry_sdk::trace::SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource)
+        .build();
+    let tracer = provider.tracer("nodalync");
+    let _ = TRACER_PROVIDER.set(provider);
+    Ok(tracer)
+}
+
+// =============================================================================
+// Log Files
+// =============================================================================
+
+/// A log file that rotates by size.
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: u32,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    /// Open (or create) the log file at `path`, appending to it.
+    pub fn open(path: &Path, max_bytes: u64, max_files: u32) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_bytes,
+            max_files,
+            file,
+            size,
+        })
+    }
+
+    /// Append one line, rotating first if it would exceed the size limit.
+    pub fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(line)?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Shift `nodalync.log.N` to `.N+1`, dropping the oldest, and start a
+    /// new current file.
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.max_files == 0 {
+            self.file.set_len(0)?;
+            self.size = 0;
+            return Ok(());
+        }
+        let _ = std::fs::remove_file(rotated_path(&self.path, self.max_files));
+        for n in (1..self.max_files).rev() {
+            let from = rotated_path(&self.path, n);
+            if from.exists() {
+                std::fs::rename(&from, rotated_path(&self.path, n + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// One line of the JSON log file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Unix timestamp in milliseconds.
+    pub ts: u64,
+    /// Level (`TRACE`, `DEBUG`, `INFO`, `WARN`, `ERROR`).
+    pub level: String,
+    /// Module path that emitted the event.
+    pub target: String,
+    /// Log message.
+    pub message: String,
+    /// Structured fields recorded with the event.
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl LogEntry {
+    /// Parsed level, if valid.
+    pub fn level(&self) -> Option<Level> {
+        Level::from_str(&self.level).ok()
+    }
+}
+
+/// Layer that writes events to a [`RotatingFile`] as JSON lines.
+struct JsonFileLayer {
+    file: Mutex<RotatingFile>,
+}
+
+impl JsonFileLayer {
+    fn new(file: RotatingFile) -> Self {
+        Self {
+            file: Mutex::new(file),
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for JsonFileLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = JsonVisitor::default();
+        event.record(&mut visitor);
+
+        let metadata = event.metadata();
+        let entry = LogEntry {
+            ts: current_timestamp(),
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            message: visitor.message.unwrap_or_default(),
+            fields: visitor.fields,
+        };
+
+        if let Ok(mut line) = serde_json::to_vec(&entry) {
+            line.push(b'\n');
+            if let Ok(mut file) = self.file.lock() {
+                // Nowhere to report a failure to write the log
+                let _ = file.write_line(&line);
+            }
+        }
+    }
+}
+
+/// Collects event fields into JSON.
+#[derive(Default)]
+struct JsonVisitor {
+    message: Option<String>,
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl JsonVisitor {
+    fn insert(&mut self, field: &Field, value: serde_json::Value) {
+        if field.name() == "message" {
+            self.message = Some(match value {
+                serde_json::Value::String(s) => s,
+                other => other.to_string(),
+            });
+        } else {
+            self.fields.insert(field.name().to_string(), value);
+        }
+    }
+}
+
+impl Visit for JsonVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.insert(field, format!("{:?}", value).into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value.into());
+    }
+}
+
+// =============================================================================
+// Reading Logs
+// =============================================================================
+
+/// Filter for reading log entries.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogFilter {
+    /// Only entries at this level or more severe.
+    pub min_level: Option<Level>,
+    /// Only entries at or after this Unix timestamp (milliseconds).
+    pub since_ms: Option<u64>,
+}
+
+impl LogFilter {
+    /// Whether an entry passes the filter.
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        if let Some(since) = self.since_ms {
+            if entry.ts < since {
+                return false;
+            }
+        }
+        match (self.min_level, entry.level()) {
+            // tracing orders levels by verbosity: ERROR < WARN < ... < TRACE
+            (Some(min), Some(level)) => level <= min,
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+}
+
+/// Read matching entries from the current and rotated log files, oldest
+/// first, keeping at most the last `limit`.
+///
+/// Lines that are not valid entries are skipped.
+pub fn read_entries(
+    base_dir: &Path,
+    max_files: u32,
+    filter: &LogFilter,
+    limit: usize,
+) -> io::Result<Vec<LogEntry>> {
+    let path = log_file_path(base_dir);
+    let mut files: Vec<PathBuf> = (1..=max_files)
+        .rev()
+        .map(|n| rotated_path(&path, n))
+        .collect();
+    files.push(path);
+
+    let mut entries = std::collections::VecDeque::new();
+    for file in files {
+        let file = match File::open(&file) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        for line in BufReader::new(file).lines() {
+            let Ok(entry) = serde_json::from_str::<LogEntry>(&line?) else {
+                continue;
+            };
+            if filter.matches(&entry) {
+                if entries.len() == limit {
+                    entries.pop_front();
+                }
+                if limit > 0 {
+                    entries.push_back(entry);
+                }
+            }
+        }
+    }
+    Ok(entries.into())
+}
+
+/// Tails the current log file, surviving rotation.
+#[derive(Debug)]
+pub struct LogFollower {
+    path: PathBuf,
+    offset: u64,
+    partial: String,
+}
+
+impl LogFollower {
+    /// Start following at the current end of the log file.
+    pub fn new(base_dir: &Path) -> Self {
+        let path = log_file_path(base_dir);
+        let offset = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        Self {
+            path,
+            offset,
+            partial: String::new(),
+        }
+    }
+
+    /// Read entries appended since the last poll.
+    pub fn poll(&mut self) -> io::Result<Vec<LogEntry>> {
+        let mut file = match File::open(&self.path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            // The file was rotated; start over on the new one
+            self.offset = 0;
+            self.partial.clear();
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut data = String::new();
+        let read = file.read_to_string(&mut data)?;
+        self.offset += read as u64;
+
+        self.partial.push_str(&data);
+        let mut entries = Vec::new();
+        while let Some(pos) = self.partial.find('\n') {
+            let line: String = self.partial.drain(..=pos).collect();
+            if let Ok(entry) = serde_json::from_str(line.trim_end()) {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Format a Unix timestamp (milliseconds) as `YYYY-MM-DD HH:MM:SS.mmm` UTC.
+pub fn format_timestamp(ms: u64) -> String {
+    let secs = ms / 1000;
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:03}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60,
+        ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(ts: u64, level: &str, message: &str) -> LogEntry {
+        LogEntry {
+            ts,
+            level: level.to_string(),
+            target: "nodalync_cli::test".to_string(),
+            message: message.to_string(),
+            fields: serde_json::Map::new(),
+        }
+    }
+
+    fn write_entry(file: &mut RotatingFile, entry: &LogEntry) {
+        let mut line = serde_json::to_vec(entry).unwrap();
+        line.push(b'\n');
+        file.write_line(&line).unwrap();
+    }
+
+    #[test]
+    fn test_rotation_keeps_max_files() {
+        let temp = TempDir::new().unwrap();
+        let path = log_file_path(temp.path());
+        let mut file = RotatingFile::open(&path, 200, 2).unwrap();
+
+        for i in 0..20 {
+            write_entry(&mut file, &entry(i, "INFO", "a line long enough to rotate"));
+        }
+
+        assert!(path.exists());
+        assert!(rotated_path(&path, 1).exists());
+        assert!(rotated_path(&path, 2).exists());
+        assert!(!rotated_path(&path, 3).exists());
+        assert!(std::fs::metadata(&path).unwrap().len() <= 200);
+    }
+
+    #[test]
+    fn test_read_entries_filters_and_orders() {
+        let temp = TempDir::new().unwrap();
+        let path = log_file_path(temp.path());
+        let mut file = RotatingFile::open(&path, 300, 5).unwrap();
+
+        write_entry(&mut file, &entry(1_000, "INFO", "started"));
+        write_entry(&mut file, &entry(2_000, "WARN", "slow peer"));
+        write_entry(&mut file, &entry(3_000, "DEBUG", "details"));
+        write_entry(&mut file, &entry(4_000, "ERROR", "failed"));
+        // Garbage lines are skipped
+        file.write_line(b"not json\n").unwrap();
+
+        let all = read_entries(temp.path(), 5, &LogFilter::default(), 100).unwrap();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[0].message, "started");
+        assert_eq!(all[3].message, "failed");
+
+        let warn = LogFilter {
+            min_level: Some(Level::WARN),
+            since_ms: None,
+        };
+        let entries = read_entries(temp.path(), 5, &warn, 100).unwrap();
+        let messages: Vec<_> = entries.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["slow peer", "failed"]);
+
+        let recent = LogFilter {
+            min_level: None,
+            since_ms: Some(3_000),
+        };
+        assert_eq!(read_entries(temp.path(), 5, &recent, 100).unwrap().len(), 2);
+
+        let last = read_entries(temp.path(), 5, &LogFilter::default(), 1).unwrap();
+        assert_eq!(last.len(), 1);
+        assert_eq!(last[0].message, "failed");
+    }
+
+    #[test]
+    fn test_follower_reads_appended_lines() {
+        let temp = TempDir::new().unwrap();
+        let path = log_file_path(temp.path());
+        let mut file = RotatingFile::open(&path, 1024 * 1024, 2).unwrap();
+        write_entry(&mut file, &entry(1, "INFO", "before"));
+
+        let mut follower = LogFollower::new(temp.path());
+        assert!(follower.poll().unwrap().is_empty());
+
+        write_entry(&mut file, &entry(2, "INFO", "after"));
+        let entries = follower.poll().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].message, "after");
+    }
+
+    #[test]
+    fn test_json_layer_records_fields() {
+        let temp = TempDir::new().unwrap();
+        let path = log_file_path(temp.path());
+        let layer = JsonFileLayer::new(RotatingFile::open(&path, 1024 * 1024, 2).unwrap());
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(peers = 3, "low peer count");
+        });
+
+        let entries = read_entries(temp.path(), 2, &LogFilter::default(), 10).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].level, "WARN");
+        assert_eq!(entries[0].message, "low peer count");
+        assert_eq!(entries[0].fields["peers"], 3);
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00.000");
+        assert_eq!(
+            format_timestamp(1_700_000_000_123),
+            "2023-11-14 22:13:20.123"
+        );
+    }
+}
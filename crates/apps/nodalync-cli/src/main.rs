@@ -2,13 +2,29 @@
 
 use clap::Parser;
 use colored::Colorize;
-use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
 use nodalync_cli::{
-    cli::{Cli, Commands},
+    cli::{
+        AllowArgs, AttributionArgs, AvailabilityArgs, BuildL2Args, Cli, CloseChannelArgs, Commands,
+        CompletionsArgs, CreateGroupArgs, CreateInvoiceArgs, DeleteArgs, DepositArgs, DidArgs,
+        DigestArgs, DisputeChannelArgs, DoctorArgs, EarningsArgs, EndorseAttributionArgs,
+        FetchGroupArgs, FetchInvoiceArgs, GrpcServerArgs, HideArgs, ImportArgs, ImportCommands,
+        InitArgs, LedgerArgs, LibraryArgs, ListArgs, ListInvoicesArgs, LogsArgs, McpServerArgs,
+        MergeL2Args, MeteringArgs, ModerationQueueArgs, NetArgs, NetCommands, OpenChannelArgs,
+        PayInvoiceArgs, PeersArgs, PeersCommands, PostBondArgs, PreviewArgs, PreviewPolicyArgs,
+        PricingArgs, PublishArgs, QueryArgs, QuotaArgs, RebalanceChannelsArgs, ReferenceArgs,
+        ReferralArgs, ReplayArgs, ReplicaArgs, ReplicaCommands, ReportArgs, ReportUsageArgs,
+        ReportsArgs, ResolveDidArgs, ResolveDisputeArgs, RetentionArgs, RetentionCommands,
+        RevokeArgs, SearchArgs, SectionsArgs, SendInvoiceArgs, ShareArgs, SimulateArgs,
+        StandingOrderCommands, StandingOrdersArgs, StartArgs, StatsArgs, StorefrontArgs, SyncArgs,
+        SyncCommands, SynthesizeArgs, TagsArgs, TenantCommands, TenantsArgs, TrialArgs, UpdateArgs,
+        UpdateGroupArgs, VerifyAttributionArgs, VerifyDidArgs, VersionsArgs, VisibilityArgs,
+        WebhookCommands, WebhooksArgs, WithdrawArgs,
+    },
     commands,
     config::{default_config_path, CliConfig},
     error::{CliError, CliResult},
+    logging,
     output::OutputFormat,
 };
 
@@ -17,15 +33,16 @@ fn main() {
     let cli = Cli::parse();
 
     // Check if this is a daemon start - must be handled before tokio runtime
-    if let Commands::Start {
+    if let Commands::Start(StartArgs {
         daemon: true,
         health,
         health_port,
-    } = &cli.command
+        bootstrap_mode,
+    }) = &cli.command
     {
         // Handle daemon mode synchronously before any async runtime exists
         let format: OutputFormat = cli.format.into();
-        if let Err(e) = handle_daemon_start(&cli, *health, *health_port) {
+        if let Err(e) = handle_daemon_start(&cli, *health, *health_port, *bootstrap_mode) {
             print_error(&e, format);
             std::process::exit(e.exit_code());
         }
@@ -35,29 +52,17 @@ fn main() {
     // For all other commands, use the normal async runtime
     let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
     rt.block_on(async_main(cli));
+    logging::shutdown();
 }
 
 async fn async_main(cli: Cli) {
-    // Initialize logging based on --verbose flag or RUST_LOG env var
-    let has_rust_log = std::env::var("RUST_LOG").is_ok();
-    if cli.verbose || has_rust_log {
-        let filter = if cli.verbose {
-            EnvFilter::from_default_env().add_directive("nodalync=debug".parse().unwrap())
-        } else {
-            EnvFilter::from_default_env()
-        };
-        tracing_subscriber::registry()
-            .with(fmt::layer())
-            .with(filter)
-            .init();
-    }
-
     // Extract format before run() consumes cli
     let format: OutputFormat = cli.format.into();
 
     // Run the command
     if let Err(e) = run(cli).await {
         print_error(&e, format);
+        logging::shutdown();
         std::process::exit(e.exit_code());
     }
 }
@@ -88,33 +93,26 @@ fn print_error(e: &CliError, format: OutputFormat) {
 
 /// Handle daemon start before tokio runtime is created.
 /// This avoids the "cannot start runtime from within runtime" panic.
-fn handle_daemon_start(cli: &Cli, health: bool, health_port: u16) -> CliResult<()> {
+fn handle_daemon_start(
+    cli: &Cli,
+    health: bool,
+    health_port: u16,
+    bootstrap_mode: bool,
+) -> CliResult<()> {
     use nodalync_cli::commands::start_daemon_sync;
 
-    // Initialize logging based on --verbose flag or RUST_LOG env var
-    let has_rust_log = std::env::var("RUST_LOG").is_ok();
-    if cli.verbose || has_rust_log {
-        let filter = if cli.verbose {
-            EnvFilter::from_default_env().add_directive("nodalync=debug".parse().unwrap())
-        } else {
-            EnvFilter::from_default_env()
-        };
-        tracing_subscriber::registry()
-            .with(fmt::layer())
-            .with(filter)
-            .init();
-    }
-
     // Load configuration
     let config_path = cli.config.clone().unwrap_or_else(default_config_path);
-    let config = CliConfig::load(&config_path)?;
+    let mut config = CliConfig::load(&config_path)?;
+    config.network.bootstrap_mode |= bootstrap_mode;
     let format: OutputFormat = cli.format.into();
 
-    // Call the synchronous daemon start function
+    // Call the synchronous daemon start function, which initializes logging
+    // in the child.
     // Note: On success, the parent process exits inside this call after forking.
     // The child process runs the daemon and never returns here.
     // Only on error does this function return.
-    start_daemon_sync(config, format, health, health_port)?;
+    start_daemon_sync(config, format, health, health_port, cli.verbose)?;
 
     // If we get here, something unexpected happened
     Ok(())
@@ -125,24 +123,56 @@ async fn run(cli: Cli) -> CliResult<()> {
     let config_path = cli.config.unwrap_or_else(default_config_path);
     let config = CliConfig::load(&config_path)?;
 
+    // Initialize logging. Only a running node writes to the log files.
+    let log_dir =
+        matches!(cli.command, Commands::Start(StartArgs { .. })).then(|| config.base_dir());
+    logging::init(&config.logging, log_dir.as_deref(), cli.verbose)?;
+
     // Get output format
     let format: OutputFormat = cli.format.into();
 
     // Dispatch command
     let output = match cli.command {
         // Identity commands
-        Commands::Init { wizard } => commands::init(config, format, wizard)?,
+        Commands::Init(InitArgs { wizard }) => commands::init(config, format, wizard)?,
 
         Commands::Whoami => commands::whoami(config, format)?,
 
+        Commands::Did(DidArgs { export }) => commands::did(config, format, export.as_deref())?,
+
+        Commands::ResolveDid(ResolveDidArgs { did }) => {
+            commands::resolve_did(config, format, &did)?
+        }
+
+        Commands::VerifyDid(VerifyDidArgs { file, peer }) => {
+            commands::verify_did(config, format, &file, peer.as_deref())?
+        }
+
+        Commands::Revoke(RevokeArgs { certificate }) => {
+            commands::revoke(config, format, &certificate).await?
+        }
+
         // Content management commands
-        Commands::Publish {
+        Commands::Publish(PublishArgs {
+            file,
+            title,
+            tags,
+            suggest_price: true,
+            ..
+        }) => commands::suggest_price(config, format, &file, title, tags)?,
+
+        Commands::Publish(PublishArgs {
             file,
             price,
             visibility,
             title,
             description,
-        } => {
+            at,
+            schema,
+            fields,
+            tags,
+            suggest_price: false,
+        }) => {
             commands::publish(
                 config,
                 format,
@@ -151,16 +181,47 @@ async fn run(cli: Cli) -> CliResult<()> {
                 visibility.into(),
                 title,
                 description,
+                at,
+                schema,
+                fields,
+                tags,
             )
             .await?
         }
 
-        Commands::List {
+        Commands::Import(ImportArgs { command }) => match command {
+            ImportCommands::Ipfs {
+                cid,
+                gateway,
+                price,
+                visibility,
+                title,
+                description,
+                tags,
+                pin,
+            } => {
+                commands::import_ipfs(
+                    config,
+                    format,
+                    &cid,
+                    gateway,
+                    price,
+                    visibility.into(),
+                    title,
+                    description,
+                    tags,
+                    pin,
+                )
+                .await?
+            }
+        },
+
+        Commands::List(ListArgs {
             visibility,
             content_type,
             limit,
             network,
-        } => {
+        }) => {
             commands::list(
                 config,
                 format,
@@ -172,88 +233,409 @@ async fn run(cli: Cli) -> CliResult<()> {
             .await?
         }
 
-        Commands::Update {
+        Commands::Update(UpdateArgs {
             hash,
             file,
             title,
             price,
-        } => commands::update(config, format, &hash, &file, title, price)?,
+        }) => commands::update(config, format, &hash, &file, title, price)?,
+
+        Commands::Visibility(VisibilityArgs {
+            hash,
+            level,
+            groups,
+        }) => commands::visibility(config, format, &hash, level.into(), &groups).await?,
+
+        Commands::PreviewPolicy(PreviewPolicyArgs {
+            hash,
+            patterns,
+            sections,
+            clear,
+        }) => commands::preview_policy(config, format, &hash, &patterns, &sections, clear)?,
+
+        Commands::Pricing(PricingArgs {
+            hash,
+            surge_max,
+            surge_step,
+            window,
+            decay_from,
+            decay_hours,
+            fixed,
+        }) => {
+            let rule =
+                commands::pricing_rule(surge_max, surge_step, window, decay_from, decay_hours);
+            commands::pricing(config, format, &hash, rule, fixed)?
+        }
+
+        Commands::Sections(SectionsArgs {
+            hash,
+            prices,
+            clear,
+        }) => commands::sections(config, format, &hash, &prices, clear)?,
+
+        Commands::Metering(MeteringArgs { hash, unit_kb, off }) => {
+            commands::metering(config, format, &hash, unit_kb, off)?
+        }
+
+        Commands::Trial(TrialArgs {
+            hash,
+            reads,
+            bytes,
+            off,
+        }) => commands::trial(config, format, &hash, reads, bytes, off)?,
 
-        Commands::Visibility { hash, level } => {
-            commands::visibility(config, format, &hash, level.into()).await?
+        Commands::Quota(QuotaArgs {
+            hash,
+            queries,
+            bytes,
+            off,
+            reset,
+        }) => commands::quota(config, format, &hash, queries, bytes, off, reset.as_deref())?,
+
+        Commands::Referral(ReferralArgs { hash, percent, off }) => {
+            commands::referral(config, format, &hash, percent, off)?
         }
 
-        Commands::Versions { hash } => commands::versions(config, format, &hash)?,
+        Commands::Share(ShareArgs {
+            hash,
+            peer,
+            expires_in,
+        }) => commands::share(config, format, &hash, peer.as_deref(), expires_in)?,
+
+        Commands::Versions(VersionsArgs { hash, repair }) => {
+            commands::versions(config, format, &hash, repair).await?
+        }
 
-        Commands::Delete { hash, force } => commands::delete(config, format, &hash, force)?,
+        Commands::Delete(DeleteArgs { hash, force }) => {
+            commands::delete(config, format, &hash, force).await?
+        }
 
         // Discovery & query commands
-        Commands::Preview { hash } => commands::preview(config, format, &hash).await?,
+        Commands::Preview(PreviewArgs { hash }) => commands::preview(config, format, &hash).await?,
 
-        Commands::Query { hash, output } => commands::query(config, format, &hash, output).await?,
+        Commands::Query(QueryArgs {
+            hash,
+            output,
+            capability,
+            section,
+            metered,
+            until,
+            max_bytes,
+            trial,
+            referrer,
+        }) => {
+            let metered = metered.then(|| commands::query::MeteredRead { until, max_bytes });
+            commands::query(
+                config, format, &hash, output, capability, section, metered, trial, referrer,
+            )
+            .await?
+        }
+
+        Commands::Library(LibraryArgs {
+            search,
+            open,
+            output,
+        }) => commands::library(config, format, search.as_deref(), open.as_deref(), output).await?,
+
+        Commands::ReportUsage(ReportUsageArgs {
+            hash,
+            bytes_read,
+            context_tokens,
+            rating,
+        }) => {
+            commands::report_usage(config, format, &hash, bytes_read, context_tokens, rating)
+                .await?
+        }
 
         // Synthesis commands
-        Commands::Synthesize {
+        Commands::Synthesize(SynthesizeArgs {
             sources,
             output,
             title,
             price,
             publish,
-        } => commands::synthesize(config, format, &sources, &output, title, price, publish).await?,
+        }) => {
+            commands::synthesize(config, format, &sources, &output, title, price, publish).await?
+        }
 
-        Commands::BuildL2 { sources, title } => {
+        Commands::BuildL2(BuildL2Args { sources, title }) => {
             commands::build_l2(config, format, &sources, title)?
         }
 
-        Commands::MergeL2 { graphs, title } => commands::merge_l2(config, format, &graphs, title)?,
+        Commands::MergeL2(MergeL2Args { graphs, title }) => {
+            commands::merge_l2(config, format, &graphs, title)?
+        }
+
+        Commands::Reference(ReferenceArgs { hash }) => commands::reference(config, format, &hash)?,
+
+        Commands::Attribution(AttributionArgs { hash, export }) => {
+            commands::attribution(config, format, &hash, export.as_deref()).await?
+        }
 
-        Commands::Reference { hash } => commands::reference(config, format, &hash)?,
+        Commands::VerifyAttribution(VerifyAttributionArgs { file }) => {
+            commands::verify_attribution(config, format, &file)?
+        }
+
+        Commands::EndorseAttribution(EndorseAttributionArgs { file }) => {
+            commands::endorse_attribution(config, format, &file)?
+        }
 
         // Economics commands
         Commands::Balance => commands::balance(config, format).await?,
 
-        Commands::Earnings { content, limit } => {
+        Commands::Earnings(EarningsArgs { content, limit }) => {
             commands::earnings(config, format, content, limit)?
         }
 
-        Commands::Deposit { amount } => commands::deposit(config, format, amount).await?,
+        Commands::Stats(StatsArgs { hash }) => commands::stats(config, format, &hash)?,
+
+        Commands::Ledger(LedgerArgs {
+            account,
+            since,
+            limit,
+            export,
+        }) => commands::ledger(config, format, account, since, limit, export.as_deref()).await?,
+
+        Commands::Deposit(DepositArgs { amount }) => {
+            commands::deposit(config, format, amount).await?
+        }
+
+        Commands::Withdraw(WithdrawArgs { amount }) => {
+            commands::withdraw(config, format, amount).await?
+        }
+
+        Commands::PostBond(PostBondArgs { amount }) => {
+            commands::post_bond(config, format, amount).await?
+        }
 
-        Commands::Withdraw { amount } => commands::withdraw(config, format, amount).await?,
+        Commands::Bond => commands::bond(config, format).await?,
 
         Commands::Settle => commands::settle(config, format).await?,
 
+        Commands::Simulate(SimulateArgs {
+            seed,
+            payments,
+            contributors,
+            sources,
+            derived,
+            max_inputs,
+            max_price,
+            batch_size,
+            top,
+        }) => {
+            let args = commands::simulate::SimulateArgs {
+                seed,
+                payments,
+                contributors,
+                sources,
+                derived,
+                max_inputs,
+                max_price,
+                batch_size,
+                top,
+            };
+            commands::simulate(format, args)?
+        }
+
         // Channel commands
-        Commands::OpenChannel { peer_id, deposit } => {
+        Commands::OpenChannel(OpenChannelArgs { peer_id, deposit }) => {
             commands::open_channel(config, format, &peer_id, deposit).await?
         }
 
-        Commands::CloseChannel { peer_id } => {
+        Commands::CloseChannel(CloseChannelArgs { peer_id }) => {
             commands::close_channel(config, format, &peer_id).await?
         }
 
-        Commands::DisputeChannel { peer_id } => {
+        Commands::DisputeChannel(DisputeChannelArgs { peer_id }) => {
             commands::dispute_channel(config, format, &peer_id).await?
         }
 
-        Commands::ResolveDispute { peer_id } => {
+        Commands::ResolveDispute(ResolveDisputeArgs { peer_id }) => {
             commands::resolve_dispute(config, format, &peer_id).await?
         }
 
-        Commands::ListChannels => commands::list_channels(config, format)?,
+        Commands::ListChannels => commands::list_channels(config, format).await?,
+        Commands::RebalanceChannels(RebalanceChannelsArgs { dry_run }) => {
+            commands::rebalance_channels(config, format, dry_run).await?
+        }
+
+        // Invoice commands
+        Commands::CreateInvoice(CreateInvoiceArgs {
+            amount,
+            memo,
+            reference,
+            expires_in,
+        }) => commands::create_invoice(
+            config,
+            format,
+            amount,
+            memo,
+            reference.as_deref(),
+            expires_in,
+        )?,
+
+        Commands::ListInvoices(ListInvoicesArgs { direction, unpaid }) => {
+            commands::list_invoices(config, format, direction, unpaid)?
+        }
+
+        Commands::SendInvoice(SendInvoiceArgs { hash, peer_id }) => {
+            commands::send_invoice(config, format, &hash, &peer_id).await?
+        }
+
+        Commands::FetchInvoice(FetchInvoiceArgs { peer_id, hash }) => {
+            commands::fetch_invoice(config, format, &peer_id, &hash).await?
+        }
+
+        Commands::PayInvoice(PayInvoiceArgs { hash }) => {
+            commands::pay_invoice(config, format, &hash).await?
+        }
+
+        // Group commands
+        Commands::CreateGroup(CreateGroupArgs { name, members }) => {
+            commands::create_group(config, format, &name, &members)?
+        }
+
+        Commands::UpdateGroup(UpdateGroupArgs { id, add, remove }) => {
+            commands::update_group(config, format, &id, &add, &remove)?
+        }
+
+        Commands::ListGroups => commands::list_groups(config, format)?,
+
+        Commands::FetchGroup(FetchGroupArgs { owner, id }) => {
+            commands::fetch_group(config, format, &owner, &id).await?
+        }
+
+        // Moderation commands
+        Commands::Report(ReportArgs {
+            hash,
+            reason,
+            comment,
+        }) => commands::report(config, format, &hash, reason.into(), &comment).await?,
+
+        Commands::ModerationQueue(ModerationQueueArgs { all }) => {
+            commands::moderation_queue(config, format, all)?
+        }
+
+        Commands::Reports(ReportsArgs { hash }) => commands::reports(config, format, &hash)?,
+
+        Commands::Hide(HideArgs { hash }) => commands::hide(config, format, &hash)?,
+
+        Commands::Allow(AllowArgs { hash }) => commands::allow(config, format, &hash)?,
 
         // Node management commands
-        Commands::Start {
+        Commands::Start(StartArgs {
             daemon,
             health,
             health_port,
-        } => commands::start(config, format, daemon, health, health_port).await?,
+            bootstrap_mode,
+        }) => {
+            let mut config = config;
+            config.network.bootstrap_mode |= bootstrap_mode;
+            commands::start(config, format, daemon, health, health_port).await?
+        }
 
         Commands::Status => commands::status(config, format).await?,
 
         Commands::Stop => commands::stop(config, format).await?,
 
+        Commands::Doctor(DoctorArgs { scrub }) => commands::doctor(config, format, scrub).await?,
+
+        Commands::Retention(RetentionArgs { command }) => match command {
+            RetentionCommands::Status => commands::retention_status(config, format)?,
+        },
+
+        Commands::Webhooks(WebhooksArgs { command }) => match command {
+            WebhookCommands::List { status, limit } => {
+                commands::list_webhooks(config, format, status, limit)?
+            }
+        },
+
+        Commands::StandingOrders(StandingOrdersArgs { command }) => match command {
+            StandingOrderCommands::Add {
+                tag,
+                max_price,
+                min_reputation,
+                budget,
+            } => commands::add_standing_order(
+                config,
+                format,
+                &tag,
+                max_price,
+                min_reputation,
+                budget,
+            )?,
+            StandingOrderCommands::List => commands::list_standing_orders(config, format)?,
+            StandingOrderCommands::Remove { id } => {
+                commands::remove_standing_order(config, format, id)?
+            }
+            StandingOrderCommands::Purchases { order, limit } => {
+                commands::standing_order_purchases(config, format, order, limit)?
+            }
+        },
+
+        Commands::Tenants(TenantsArgs { command }) => match command {
+            TenantCommands::Create { id, name, budget } => {
+                commands::create_tenant(config, format, &id, name.as_deref(), budget)?
+            }
+            TenantCommands::List => commands::list_tenants(config, format)?,
+            TenantCommands::Show { id } => commands::show_tenant(config, format, &id)?,
+            TenantCommands::Budget { id, budget } => {
+                commands::set_tenant_budget(config, format, &id, budget)?
+            }
+            TenantCommands::RotateKey { id } => commands::rotate_tenant_key(config, format, &id)?,
+            TenantCommands::Remove { id } => commands::remove_tenant(config, format, &id)?,
+            TenantCommands::Assign { hash, tenant } => {
+                commands::assign_tenant_content(config, format, &hash, tenant.as_deref())?
+            }
+            TenantCommands::Purchases { tenant, limit } => {
+                commands::tenant_purchases(config, format, tenant.as_deref(), limit)?
+            }
+        },
+
+        Commands::Digest(DigestArgs { period, send }) => {
+            commands::digest(config, format, period, send).await?
+        }
+
+        Commands::Sync(SyncArgs { command }) => match command {
+            SyncCommands::Export { file } => commands::export_sync(config, format, &file)?,
+            SyncCommands::Import { file } => commands::import_sync(config, format, &file)?,
+            SyncCommands::Push { peer } => commands::push_sync(config, format, &peer).await?,
+            SyncCommands::Pull { peer } => commands::pull_sync(config, format, &peer).await?,
+        },
+
+        Commands::Replica(ReplicaArgs { command }) => match command {
+            ReplicaCommands::Export {
+                replica,
+                file,
+                days,
+            } => commands::export_replica(config, format, &replica, &file, days)?,
+            ReplicaCommands::Import { file } => commands::import_replica(config, format, &file)?,
+            ReplicaCommands::List => commands::list_replicas(config, format)?,
+        },
+
+        Commands::Logs(LogsArgs {
+            follow,
+            level,
+            since,
+            lines,
+        }) => commands::logs(config, format, follow, level, since, lines).await?,
+
+        Commands::Replay(ReplayArgs { log }) => commands::replay(format, &log).await?,
+
+        Commands::Net(NetArgs { command }) => match command {
+            NetCommands::Inspect { file } => commands::inspect_capture(format, &file)?,
+        },
+
+        Commands::Peers(PeersArgs { command }) => match command {
+            PeersCommands::List => commands::list_peers(config, format)?,
+        },
+
+        Commands::Availability(AvailabilityArgs { failing }) => {
+            commands::list_availability(config, format, failing)?
+        }
+
         // MCP server command
-        Commands::McpServer {
+        Commands::McpServer(McpServerArgs {
             budget,
             auto_approve,
             enable_network,
@@ -261,40 +643,79 @@ async fn run(cli: Cli) -> CliResult<()> {
             hedera_private_key,
             hedera_contract_id,
             hedera_network,
-        } => {
+            tenant_key,
+        }) => {
             let hedera_args = commands::mcp_server::HederaArgs {
                 account_id: hedera_account_id,
                 private_key: hedera_private_key,
                 contract_id: hedera_contract_id,
                 network: hedera_network,
             };
-            commands::mcp_server(config, budget, auto_approve, enable_network, hedera_args).await?
+            commands::mcp_server(
+                config,
+                budget,
+                auto_approve,
+                enable_network,
+                hedera_args,
+                tenant_key,
+            )
+            .await?
+        }
+
+        Commands::GrpcServer(GrpcServerArgs { listen }) => {
+            commands::grpc_server(config, listen).await?
         }
 
         // Search command
-        Commands::Search {
+        Commands::Search(SearchArgs {
             query,
             content_type,
             limit,
             all,
-        } => {
-            commands::search(
+            min_price,
+            max_price,
+            tags,
+            owner,
+            after,
+        }) => {
+            let filters = commands::search::search_filters(
+                content_type.map(Into::into),
+                min_price,
+                max_price,
+                tags,
+                owner.as_deref(),
+                after,
+            )?;
+            commands::search(config, format, &query, filters, limit, all).await?
+        }
+
+        Commands::Tags(TagsArgs { prefix, limit }) => {
+            commands::tags(config, format, prefix.as_deref(), limit)?
+        }
+
+        Commands::Storefront(StorefrontArgs {
+            publisher,
+            category,
+            publish,
+        }) => {
+            commands::storefront(
                 config,
                 format,
-                &query,
-                content_type.map(Into::into),
-                limit,
-                all,
+                publisher.as_deref(),
+                category.as_deref(),
+                publish,
             )
             .await?
         }
 
         // Completions command
-        Commands::Completions { shell } => commands::completions(shell)?,
+        Commands::Completions(CompletionsArgs { shell }) => commands::completions(shell)?,
     };
 
-    // Print output
-    println!("{}", output);
+    // Print output (commands that stream their output return nothing)
+    if !output.is_empty() {
+        println!("{}", output);
+    }
 
     Ok(())
 }
@@ -29,6 +29,24 @@ pub struct Metrics {
     /// Total GossipSub messages received.
     pub gossipsub_messages_total: IntCounter,
 
+    /// Total incoming announcements dropped by the spam filter, by reason.
+    pub announcements_dropped_total: IntCounterVec,
+
+    /// Total connections established by direction (inbound/outbound).
+    pub connections_total: IntCounterVec,
+
+    /// Total inbound connections and requests rejected, by kind.
+    pub rate_limited_total: IntCounterVec,
+
+    /// Currently open connections.
+    pub active_connections: IntGauge,
+
+    /// Distinct remote IPs with open inbound connections.
+    pub connected_ips: IntGauge,
+
+    /// Records held in the local DHT store.
+    pub dht_records: IntGauge,
+
     // =========================================================================
     // Settlement Metrics
     // =========================================================================
@@ -96,6 +114,51 @@ impl Metrics {
         ))
         .expect("metric creation should not fail");
 
+        let connections_total = IntCounterVec::new(
+            Opts::new(
+                "nodalync_connections_total",
+                "Total connections established",
+            ),
+            &["direction"],
+        )
+        .expect("metric creation should not fail");
+
+        let rate_limited_total = IntCounterVec::new(
+            Opts::new(
+                "nodalync_rate_limited_total",
+                "Total inbound connections, requests and broadcasts rejected",
+            ),
+            &["kind"],
+        )
+        .expect("metric creation should not fail");
+
+        let announcements_dropped_total = IntCounterVec::new(
+            Opts::new(
+                "nodalync_announcements_dropped_total",
+                "Total incoming announcements dropped by the spam filter or backpressure",
+            ),
+            &["reason"],
+        )
+        .expect("metric creation should not fail");
+
+        let active_connections = IntGauge::with_opts(Opts::new(
+            "nodalync_active_connections",
+            "Currently open connections",
+        ))
+        .expect("metric creation should not fail");
+
+        let connected_ips = IntGauge::with_opts(Opts::new(
+            "nodalync_connected_ips",
+            "Distinct remote IPs with open inbound connections",
+        ))
+        .expect("metric creation should not fail");
+
+        let dht_records = IntGauge::with_opts(Opts::new(
+            "nodalync_dht_records",
+            "Records held in the local DHT store",
+        ))
+        .expect("metric creation should not fail");
+
         // Settlement metrics
         let contract_balance_tinybars = IntGauge::with_opts(Opts::new(
             "nodalync_contract_balance_tinybars",
@@ -178,6 +241,24 @@ impl Metrics {
         registry
             .register(Box::new(gossipsub_messages_total.clone()))
             .expect("registration should not fail");
+        registry
+            .register(Box::new(connections_total.clone()))
+            .expect("registration should not fail");
+        registry
+            .register(Box::new(rate_limited_total.clone()))
+            .expect("registration should not fail");
+        registry
+            .register(Box::new(announcements_dropped_total.clone()))
+            .expect("registration should not fail");
+        registry
+            .register(Box::new(active_connections.clone()))
+            .expect("registration should not fail");
+        registry
+            .register(Box::new(connected_ips.clone()))
+            .expect("registration should not fail");
+        registry
+            .register(Box::new(dht_records.clone()))
+            .expect("registration should not fail");
         registry
             .register(Box::new(contract_balance_tinybars.clone()))
             .expect("registration should not fail");
@@ -212,6 +293,12 @@ impl Metrics {
             peer_events_total,
             dht_operations_total,
             gossipsub_messages_total,
+            connections_total,
+            rate_limited_total,
+            announcements_dropped_total,
+            active_connections,
+            connected_ips,
+            dht_records,
             contract_balance_tinybars,
             settlement_batches_total,
             settlement_errors_total,
@@ -235,6 +322,75 @@ impl Metrics {
         String::from_utf8(buffer).expect("metrics are valid utf8")
     }
 
+    /// Update connection metrics from a network statistics snapshot.
+    ///
+    /// The network layer keeps cumulative totals, so counters are advanced
+    /// by the difference since the last update.
+    pub fn record_connection_stats(&self, stats: &nodalync_net::ConnectionStats) {
+        let advance = |counter: IntCounter, total: u64| {
+            let delta = total.saturating_sub(counter.get());
+            if delta > 0 {
+                counter.inc_by(delta);
+            }
+        };
+        advance(
+            self.connections_total.with_label_values(&["inbound"]),
+            stats.inbound_total,
+        );
+        advance(
+            self.connections_total.with_label_values(&["outbound"]),
+            stats.outbound_total,
+        );
+        advance(
+            self.rate_limited_total.with_label_values(&["connection"]),
+            stats.rejected_connections_total,
+        );
+        advance(
+            self.rate_limited_total.with_label_values(&["request"]),
+            stats.rejected_requests_total,
+        );
+        advance(
+            self.rate_limited_total.with_label_values(&["broadcast"]),
+            stats.dropped_broadcasts_total,
+        );
+        self.active_connections.set(stats.active_connections as i64);
+        self.connected_ips.set(stats.connected_ips as i64);
+        self.dht_records.set(stats.dht_records as i64);
+    }
+
+    /// Update announcement filter counters from the node's totals.
+    ///
+    /// Like [`Self::record_connection_stats`], counters are advanced by the
+    /// difference since the last update.
+    pub fn record_announcement_stats(&self, stats: &nodalync_ops::AnnouncementFilterStats) {
+        for (reason, total) in [
+            ("rate_limited", stats.dropped_rate_limited),
+            ("invalid_signature", stats.dropped_invalid_signature),
+            ("price", stats.dropped_price),
+            ("title", stats.dropped_title),
+            ("reputation", stats.dropped_reputation),
+        ] {
+            let counter = self
+                .announcements_dropped_total
+                .with_label_values(&[reason]);
+            let delta = total.saturating_sub(counter.get());
+            if delta > 0 {
+                counter.inc_by(delta);
+            }
+        }
+    }
+
+    /// Record announcements dropped because the ingestion queue was full.
+    pub fn record_ingest_stats(&self, stats: &nodalync_ops::AnnouncementIngestStats) {
+        let counter = self
+            .announcements_dropped_total
+            .with_label_values(&["backpressure"]);
+        let delta = stats.dropped_backpressure.saturating_sub(counter.get());
+        if delta > 0 {
+            counter.inc_by(delta);
+        }
+    }
+
     /// Record a settlement error by its type label.
     pub fn record_settlement_error(&self, error: &nodalync_settle::SettleError) {
         let label = Self::error_to_label(error);
@@ -318,6 +474,57 @@ mod tests {
         assert!(output.contains("nodalync_dht_operations_total"));
     }
 
+    #[test]
+    fn test_record_connection_stats() {
+        let metrics = Metrics::new();
+        let mut stats = nodalync_net::ConnectionStats {
+            inbound_total: 3,
+            outbound_total: 2,
+            rejected_connections_total: 1,
+            active_connections: 4,
+            ..Default::default()
+        };
+        metrics.record_connection_stats(&stats);
+        stats.inbound_total = 5;
+        metrics.record_connection_stats(&stats);
+
+        assert_eq!(
+            metrics
+                .connections_total
+                .with_label_values(&["inbound"])
+                .get(),
+            5
+        );
+        assert_eq!(metrics.active_connections.get(), 4);
+        let output = metrics.encode();
+        assert!(output.contains("nodalync_rate_limited_total{kind=\"connection\"} 1"));
+    }
+
+    #[test]
+    fn test_record_announcement_stats() {
+        let metrics = Metrics::new();
+        let mut stats = nodalync_ops::AnnouncementFilterStats {
+            accepted: 10,
+            dropped_price: 2,
+            ..Default::default()
+        };
+        metrics.record_announcement_stats(&stats);
+        stats.dropped_price = 3;
+        stats.dropped_title = 1;
+        metrics.record_announcement_stats(&stats);
+
+        let output = metrics.encode();
+        assert!(output.contains("nodalync_announcements_dropped_total{reason=\"price\"} 3"));
+        assert!(output.contains("nodalync_announcements_dropped_total{reason=\"title\"} 1"));
+
+        metrics.record_ingest_stats(&nodalync_ops::AnnouncementIngestStats {
+            dropped_backpressure: 4,
+            ..Default::default()
+        });
+        let output = metrics.encode();
+        assert!(output.contains("nodalync_announcements_dropped_total{reason=\"backpressure\"} 4"));
+    }
+
     #[test]
     fn test_settlement_error_labels() {
         use nodalync_settle::SettleError;
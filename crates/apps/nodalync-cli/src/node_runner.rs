@@ -8,7 +8,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use nodalync_net::{InboundRequestId, Network, NetworkEvent, NetworkNode};
-use nodalync_ops::CloseResult;
+use nodalync_ops::{CloseResult, TopUpOutcome};
 use nodalync_store::ChannelStore;
 use nodalync_wire::MessageType;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
@@ -18,9 +18,13 @@ use tokio::time::interval;
 use tracing::{debug, error, info, warn};
 
 use crate::alerting::AlertManager;
+use crate::bridge::{refresh_feed, run_bridge_server, run_feed_ingest, BridgeSite, SharedFeed};
+use crate::commands::doctor::{run_scrub, scrub_report_path, write_scrub_report};
 use crate::config::AlertingConfig;
 use crate::context::NodeContext;
 use crate::error::{CliError, CliResult};
+use crate::health::{Component, HealthReport, HealthTracker, SharedHealth};
+use crate::ipc::{next_ipc_call, remove_ipc_endpoint, spawn_ipc_server};
 use crate::metrics::{Metrics, SharedMetrics};
 
 // =============================================================================
@@ -189,6 +193,9 @@ pub struct RuntimeStatus {
     pub connected_peers: u32,
     /// Unix timestamp when status was last updated.
     pub updated_at: u64,
+    /// Structured component health (absent in files from older nodes).
+    #[serde(default)]
+    pub health: Option<HealthReport>,
 }
 
 /// Get the status file path for the given base directory.
@@ -296,14 +303,19 @@ pub async fn run_event_loop_with_health(
 ) -> CliResult<()> {
     info!("Starting node event loop");
 
-    let network = ctx
-        .network
-        .as_ref()
-        .ok_or(CliError::config("Network not initialized"))?;
+    // Hold our own handle so control requests can borrow the whole context
+    let network = &Arc::clone(
+        ctx.network
+            .as_ref()
+            .ok_or(CliError::config("Network not initialized"))?,
+    );
 
     // Status file path (if base_dir provided)
     let status_path = base_dir.map(status_file_path);
 
+    // Bootstrap nodes take no payments, so there is nothing to settle
+    let bootstrap_mode = ctx.config.network.bootstrap_mode;
+
     // Status update interval (every 5 seconds)
     let mut status_interval = interval(Duration::from_secs(5));
 
@@ -312,6 +324,83 @@ pub async fn run_event_loop_with_health(
     // Skip the first immediate tick
     settlement_interval.tick().await;
 
+    // Settlement balance top-up interval (only ticks when enabled)
+    let top_up_enabled = ctx.ops.config.top_up.enabled && !bootstrap_mode;
+    let mut top_up_interval = interval(Duration::from_secs(
+        ctx.ops.config.top_up.check_interval_secs.max(1),
+    ));
+
+    // Channel dispute watch interval (only ticks when enabled)
+    let disputes_enabled = ctx.ops.config.disputes.enabled && !bootstrap_mode;
+    let mut disputes_interval = interval(Duration::from_secs(
+        ctx.ops.config.disputes.check_interval_secs.max(1),
+    ));
+
+    // Data retention interval (only ticks when a category has a TTL)
+    let retention_enabled = !ctx.ops.config.retention.ttls.is_empty();
+    let mut retention_interval = interval(Duration::from_secs(
+        ctx.ops.config.retention.check_interval_secs.max(1),
+    ));
+
+    // Clock skew probe interval
+    let mut clock_interval = interval(Duration::from_secs(
+        ctx.ops.config.clock.probe_interval_secs.max(1),
+    ));
+
+    // Peer latency probe interval
+    let qos_enabled = ctx.ops.config.qos.enabled;
+    let mut qos_interval = interval(Duration::from_secs(
+        ctx.ops.config.qos.probe_interval_secs.max(1),
+    ));
+
+    // Content availability check interval
+    let availability_enabled = ctx.ops.config.availability.enabled;
+    let mut availability_interval = interval(Duration::from_secs(
+        ctx.ops.config.availability.check_interval_secs.max(1),
+    ));
+
+    // Storefront republish interval
+    let storefront_enabled = ctx.ops.config.storefront.enabled;
+    let mut storefront_interval = interval(Duration::from_secs(
+        ctx.ops.config.storefront.refresh_interval_secs.max(1),
+    ));
+
+    // Webhook delivery interval (only ticks when endpoints are registered)
+    let webhooks_enabled = ctx.ops.config.webhooks.is_enabled();
+    let mut webhook_interval = interval(Duration::from_secs(
+        ctx.ops.config.webhooks.delivery_interval_secs.max(1),
+    ));
+
+    // Activity digest check interval (only ticks when a period is enabled)
+    let digests_enabled = ctx.ops.config.notifications.is_enabled();
+    let mut digest_interval = interval(Duration::from_secs(
+        ctx.ops.config.notifications.check_interval_secs.max(1),
+    ));
+
+    // Popularity prewarm interval
+    let mut prewarm_interval = interval(Duration::from_secs(
+        ctx.ops.config.popularity.prewarm_interval_secs.max(1),
+    ));
+
+    // Standing order interval (only ticks when enabled)
+    let standing_orders_enabled = ctx.ops.config.standing_orders.enabled && !bootstrap_mode;
+    let mut standing_order_interval = interval(Duration::from_secs(
+        ctx.ops.config.standing_orders.interval_secs.max(1),
+    ));
+
+    // Earliest scheduled publish to announce, if any
+    let mut next_publish = next_scheduled_publish(ctx);
+
+    // Bridge feed refresh interval (only ticks when the bridge is enabled)
+    let bridge_enabled = ctx.config.bridge.enabled;
+    let mut bridge_interval = interval(Duration::from_secs(BRIDGE_REFRESH_SECS));
+
+    // Content integrity scrub interval (0 hours disables it)
+    let scrub_hours = ctx.config.storage.scrub_interval_hours;
+    let mut scrub_interval = interval(Duration::from_secs(scrub_hours.max(1) * 60 * 60));
+    // Skip the first immediate tick
+    scrub_interval.tick().await;
+
     // Track start time for uptime calculation
     let start_time = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -329,6 +418,10 @@ pub async fn run_event_loop_with_health(
         .with_label_values(&[version, &peer_id])
         .set(1);
 
+    // Create health tracker (fed by the event loop, read by the health server)
+    let health: SharedHealth = Arc::new(HealthTracker::new(start_time));
+    health.check(ctx, base_dir);
+
     // Create alert manager
     let alert_manager = Arc::new(AlertManager::new(
         health_config.alerting.clone(),
@@ -380,11 +473,19 @@ pub async fn run_event_loop_with_health(
         let (tx, rx) = watch::channel(false);
         let network_clone = Arc::clone(network);
         let metrics_clone = Arc::clone(&metrics);
+        let health_clone = Arc::clone(&health);
         let port = health_config.port;
 
         tokio::spawn(async move {
-            if let Err(e) =
-                run_health_server(port, network_clone, start_time, Some(metrics_clone), rx).await
+            if let Err(e) = run_health_server(
+                port,
+                network_clone,
+                start_time,
+                Some(metrics_clone),
+                health_clone,
+                rx,
+            )
+            .await
             {
                 warn!("Health server error: {}", e);
             }
@@ -400,6 +501,62 @@ pub async fn run_event_loop_with_health(
         None
     };
 
+    // Spawn the feed and ActivityPub bridge if enabled
+    let bridge_feed: SharedFeed = SharedFeed::default();
+    let bridge_shutdown_tx = if bridge_enabled {
+        let (tx, rx) = watch::channel(false);
+        let bridge_config = ctx.config.bridge.clone();
+        refresh_feed(&ctx.ops, &bridge_feed, bridge_config.max_items);
+
+        let public_key = ctx.ops.private_key().map(|k| k.public_key());
+        let site = BridgeSite::new(
+            &bridge_config,
+            ctx.ops.peer_id().to_string(),
+            public_key.as_ref(),
+        );
+        let feed_clone = Arc::clone(&bridge_feed);
+        let listen = bridge_config.listen.clone();
+        let server_rx = rx.clone();
+        tokio::spawn(async move {
+            if let Err(e) = run_bridge_server(listen, site, feed_clone, server_rx).await {
+                warn!("Bridge server error: {}", e);
+            }
+        });
+
+        if !bridge_config.feeds.is_empty() {
+            tokio::spawn(run_feed_ingest(
+                bridge_config.feeds.clone(),
+                Duration::from_secs(bridge_config.poll_interval_secs.max(60)),
+                ctx.ops.state.announcement_writer(),
+                rx,
+            ));
+        }
+
+        info!(
+            "Bridge enabled, feeds at {}/feed.rss and {}/feed.atom",
+            bridge_config.base_url(),
+            bridge_config.base_url()
+        );
+        Some(tx)
+    } else {
+        None
+    };
+
+    // Spawn control socket listener (if base_dir provided)
+    let (ipc_shutdown_tx, mut ipc_rx) = match base_dir {
+        Some(dir) => {
+            let (tx, rx) = watch::channel(false);
+            match spawn_ipc_server(dir, rx) {
+                Ok(ipc_rx) => (Some(tx), Some(ipc_rx)),
+                Err(e) => {
+                    warn!("Control interface disabled: {}", e);
+                    (None, None)
+                }
+            }
+        }
+        None => (None, None),
+    };
+
     // Helper to write status
     let write_status = |network: &Arc<NetworkNode>, path: &Option<PathBuf>| {
         if let Some(ref path) = path {
@@ -409,6 +566,7 @@ pub async fn run_event_loop_with_health(
                     .duration_since(std::time::UNIX_EPOCH)
                     .map(|d| d.as_secs())
                     .unwrap_or(0),
+                health: Some(health.report()),
             };
             if let Err(e) = write_status_file(path, &status) {
                 debug!("Failed to write status file: {}", e);
@@ -419,6 +577,15 @@ pub async fn run_event_loop_with_health(
     // Write initial status
     write_status(network, &status_path);
 
+    // Store gossiped announcements in batches
+    ctx.ops.start_announcement_ingest();
+
+    // A node that knows no content asks the peers it connects to for a
+    // snapshot, until one answers
+    let mut fast_sync_pending = ctx.config.snapshot.sync_on_start
+        && !bootstrap_mode
+        && ctx.ops.state.list_announcements().is_empty();
+
     loop {
         tokio::select! {
             // Check for shutdown signal
@@ -429,20 +596,68 @@ pub async fn run_event_loop_with_health(
                 }
             }
 
+            // Control requests from other CLI invocations
+            call = next_ipc_call(&mut ipc_rx) => {
+                match call {
+                    Some(call) => {
+                        call.handle(ctx).await;
+                        // The call may have scheduled a publish
+                        next_publish = next_scheduled_publish(ctx);
+                    }
+                    None => {
+                        warn!("Control interface stopped");
+                        ipc_rx = None;
+                    }
+                }
+            }
+
             // Periodic status update and health check
             _ = status_interval.tick() => {
                 let peer_count = network.connected_peers().len() as u32;
+                health.check(ctx, base_dir);
                 write_status(network, &status_path);
                 // Check health periodically even without peer events
                 alert_manager.check_health(peer_count).await;
             }
 
+            // Announce scheduled content when its embargo ends
+            _ = sleep_until_timestamp(next_publish) => {
+                let now = nodalync_ops::current_timestamp();
+                match ctx.ops.publish_scheduled_content(now).await {
+                    Ok(hashes) => {
+                        for hash in hashes {
+                            info!(hash = %hash, "Scheduled content published");
+                        }
+                        next_publish = next_scheduled_publish(ctx);
+                    }
+                    Err(e) => {
+                        warn!(error = %e, "Scheduled publish failed");
+                        next_publish = Some(now + SCHEDULED_PUBLISH_RETRY_MS);
+                    }
+                }
+            }
+
+            // Periodic content integrity scrub
+            _ = scrub_interval.tick(), if scrub_hours > 0 => {
+                match run_scrub(ctx).await {
+                    Ok(summary) => {
+                        if let Some(base_dir) = base_dir {
+                            if let Err(e) = write_scrub_report(&scrub_report_path(base_dir), &summary) {
+                                debug!("Failed to write scrub report: {}", e);
+                            }
+                        }
+                    }
+                    Err(e) => warn!(error = %e, "Content scrub failed"),
+                }
+            }
+
             // Periodic settlement check
-            _ = settlement_interval.tick() => {
+            _ = settlement_interval.tick(), if !bootstrap_mode => {
                 // Trigger settlement batch for any channels that have exceeded thresholds
                 match ctx.ops.trigger_settlement_batch().await {
                     Ok(Some(batch_id)) => {
                         info!(batch_id = %batch_id, "Background settlement batch submitted");
+                        health.record_success(Component::Settlement);
                     }
                     Ok(None) => {
                         // No settlement needed (threshold not reached)
@@ -450,10 +665,121 @@ pub async fn run_event_loop_with_health(
                     }
                     Err(e) => {
                         warn!(error = %e, "Background settlement batch failed");
+                        health.record_error(Component::Settlement, e.to_string());
+                    }
+                }
+
+                // Rebalance skewed channels if enabled
+                match ctx.ops.auto_rebalance_channels(&ctx.private_key).await {
+                    Ok(Some(outcomes)) => {
+                        for outcome in outcomes.iter().filter(|o| o.performed) {
+                            info!(action = ?outcome.action, "Channel rebalanced");
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        warn!(error = %e, "Channel rebalance failed");
+                    }
+                }
+            }
+
+            // Keep the settlement balance topped up
+            _ = top_up_interval.tick(), if top_up_enabled => {
+                match ctx.ops.check_top_up().await {
+                    // Deposits and cap hits are logged by ops
+                    Ok(Some(TopUpOutcome::Deposited { .. })) => {
+                        health.record_success(Component::Settlement);
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        warn!(error = %e, "Settlement top-up failed");
+                        health.record_error(Component::Settlement, e.to_string());
                     }
                 }
             }
 
+            // Watch our channels for disputes and counter stale states
+            _ = disputes_interval.tick(), if disputes_enabled => {
+                // New disputes, counters and near deadlines are logged by ops
+                if let Err(e) = ctx.ops.check_disputes(&ctx.private_key).await {
+                    warn!(error = %e, "Dispute check failed");
+                    health.record_error(Component::Settlement, e.to_string());
+                }
+            }
+
+            // Expire data past its retention TTL
+            _ = retention_interval.tick(), if retention_enabled => {
+                // Deletions are logged by ops
+                if let Err(e) = ctx.ops.enforce_retention() {
+                    warn!(error = %e, "Retention enforcement failed");
+                }
+            }
+
+            // Estimate our clock skew from peers' pongs
+            _ = clock_interval.tick() => {
+                // Skew beyond the tolerance is warned about by ops
+                if let Err(e) = ctx.ops.probe_clock_skew().await {
+                    warn!(error = %e, "Clock skew probe failed");
+                }
+            }
+
+            // Measure peers' latency for provider selection
+            _ = qos_interval.tick(), if qos_enabled => {
+                if let Err(e) = ctx.ops.probe_peer_latency().await {
+                    warn!(error = %e, "Peer latency probe failed");
+                }
+            }
+
+            // Check that our shared content is fetchable from outside
+            _ = availability_interval.tick(), if availability_enabled => {
+                // Unreachable content is warned about by ops
+                if let Err(e) = ctx.ops.check_availability().await {
+                    warn!(error = %e, "Content availability check failed");
+                }
+            }
+
+            // Keep our storefront in the DHT current with our catalog
+            _ = storefront_interval.tick(), if storefront_enabled => {
+                if let Err(e) = ctx.ops.publish_storefront().await {
+                    warn!(error = %e, "Storefront publish failed");
+                }
+            }
+
+            // Keep the bridge feeds current
+            _ = bridge_interval.tick(), if bridge_enabled => {
+                refresh_feed(&ctx.ops, &bridge_feed, ctx.config.bridge.max_items);
+            }
+
+            // Post queued event webhooks
+            _ = webhook_interval.tick(), if webhooks_enabled => {
+                // Failures are recorded per delivery and logged by ops
+                if let Err(e) = ctx.ops.deliver_webhooks().await {
+                    warn!(error = %e, "Webhook delivery failed");
+                }
+            }
+
+            // Send digests of periods that ended
+            _ = digest_interval.tick(), if digests_enabled => {
+                // Notifier failures are logged by ops and retried next tick
+                if let Err(e) = ctx.ops.send_due_digests().await {
+                    warn!(error = %e, "Activity digest failed");
+                }
+            }
+
+            // Keep popular content hot
+            _ = prewarm_interval.tick() => {
+                if let Err(e) = ctx.ops.prewarm_cache().await {
+                    warn!(error = %e, "Cache prewarm failed");
+                }
+            }
+
+            // Buy announced content matching standing orders
+            _ = standing_order_interval.tick(), if standing_orders_enabled => {
+                if let Err(e) = ctx.ops.run_standing_orders().await {
+                    warn!(error = %e, "Standing orders failed");
+                }
+            }
+
             // Process network events
             event_result = network.next_event() => {
                 match event_result {
@@ -471,6 +797,11 @@ pub async fn run_event_loop_with_health(
                             NetworkEvent::DhtPutComplete { success, .. } => {
                                 let result = if *success { "success" } else { "failure" };
                                 metrics.dht_operations_total.with_label_values(&["put", result]).inc();
+                                if *success {
+                                    health.record_success(Component::Dht);
+                                } else {
+                                    health.record_error(Component::Dht, "DHT put failed");
+                                }
                             }
                             NetworkEvent::DhtGetResult { value, .. } => {
                                 let result = if value.is_some() { "success" } else { "not_found" };
@@ -488,8 +819,36 @@ pub async fn run_event_loop_with_health(
                             NetworkEvent::PeerConnected { .. } | NetworkEvent::PeerDisconnected { .. }
                         );
 
+                        let broadcast = matches!(&event, NetworkEvent::BroadcastReceived { .. });
+
+                        let connected = match &event {
+                            NetworkEvent::PeerConnected { peer } => Some(*peer),
+                            _ => None,
+                        };
+
                         if let Err(e) = handle_event(&mut ctx.ops, Arc::clone(network), event).await {
                             warn!("Error handling event: {}", e);
+                            health.record_error(Component::Network, format!("event handling: {}", e));
+                        }
+
+                        if broadcast {
+                            metrics.record_announcement_stats(&ctx.ops.announcement_filter_stats());
+                            metrics.record_ingest_stats(&ctx.ops.announcement_ingest_stats());
+                        }
+
+                        if let (false, Some(peer)) = (bootstrap_mode, connected) {
+                            // Learn what the peer supports before relying on it
+                            if let Err(e) = ctx.ops.exchange_peer_info(peer).await {
+                                debug!(peer = %peer, error = %e, "Peer info exchange failed");
+                            }
+                        }
+
+                        if let (true, Some(peer)) = (fast_sync_pending, connected) {
+                            // Imports are logged by ops
+                            match ctx.ops.fast_sync(peer).await {
+                                Ok(_) => fast_sync_pending = false,
+                                Err(e) => debug!(peer = %peer, error = %e, "Fast sync failed"),
+                            }
                         }
 
                         // Update status and check health on peer changes
@@ -509,6 +868,16 @@ pub async fn run_event_loop_with_health(
         }
     }
 
+    // Store announcements still waiting in the ingestion queue
+    ctx.ops.flush_announcements().await;
+
+    // Keep the DHT records this node holds for the network
+    match network.persist_dht_records().await {
+        Ok(0) => {}
+        Ok(count) => info!(records = count, "Persisted DHT records"),
+        Err(e) => warn!(error = %e, "Failed to persist DHT records"),
+    }
+
     // Send shutdown alert
     let final_peer_count = network.connected_peers().len() as u32;
     alert_manager.send_shutdown_alert(final_peer_count).await;
@@ -570,25 +939,68 @@ pub async fn run_event_loop_with_health(
         let _ = tx.send(true);
     }
 
+    // Signal bridge server and feed polling to shutdown
+    if let Some(tx) = bridge_shutdown_tx {
+        let _ = tx.send(true);
+    }
+
+    // Signal control socket listener to shutdown
+    if let Some(tx) = ipc_shutdown_tx {
+        let _ = tx.send(true);
+    }
+
     // Clean up status file on exit
     if let Some(ref path) = status_path {
         let _ = remove_status_file(path);
     }
 
+    // The listener task may not get to run again before the process exits
+    if let Some(dir) = base_dir {
+        remove_ipc_endpoint(dir);
+    }
+
     info!("Event loop stopped");
     Ok(())
 }
 
+/// How often the bridge's feeds pick up content changes (seconds).
+const BRIDGE_REFRESH_SECS: u64 = 60;
+
+/// Delay before retrying a failed scheduled publish (milliseconds).
+const SCHEDULED_PUBLISH_RETRY_MS: u64 = 60_000;
+
+/// Get the earliest pending scheduled publish time.
+fn next_scheduled_publish(ctx: &NodeContext) -> Option<u64> {
+    ctx.ops.next_scheduled_publish().unwrap_or_else(|e| {
+        warn!(error = %e, "Failed to load scheduled publishes");
+        None
+    })
+}
+
+/// Sleep until a Unix timestamp (milliseconds), or forever if there is none.
+async fn sleep_until_timestamp(at: Option<u64>) {
+    match at {
+        Some(at) => {
+            let now = nodalync_ops::current_timestamp();
+            tokio::time::sleep(Duration::from_millis(at.saturating_sub(now))).await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
 /// Run a minimal HTTP health server with optional Prometheus metrics endpoint.
 ///
 /// Routes:
 /// - `GET /metrics` - Prometheus text format metrics
-/// - `GET /health` or other - JSON health status
+/// - `GET /health/live` - Liveness probe, always 200 while the process serves
+/// - `GET /health/ready` - Readiness probe, 503 when a critical component is down
+/// - `GET /health` or other - Structured JSON health report (503 when down)
 async fn run_health_server(
     port: u16,
     network: Arc<NetworkNode>,
     start_time: u64,
     metrics: Option<SharedMetrics>,
+    health: SharedHealth,
     mut shutdown_rx: watch::Receiver<bool>,
 ) -> CliResult<()> {
     let addr = format!("0.0.0.0:{}", port);
@@ -631,33 +1043,48 @@ async fn run_health_server(
                             .and_then(|line| line.split_whitespace().nth(1))
                             .unwrap_or("/health");
 
-                        let (content_type, body) = if path == "/metrics" {
-                            if let Some(ref m) = metrics {
-                                // Update uptime before encoding
-                                m.uptime_seconds.set(uptime_secs as i64);
-                                m.connected_peers.set(connected_peers as i64);
-                                ("text/plain; version=0.0.4; charset=utf-8", m.encode())
-                            } else {
-                                // Metrics not enabled, return empty
-                                ("text/plain", String::from("# Metrics not enabled\n"))
+                        let (status_code, content_type, body) = match path {
+                            "/metrics" => {
+                                if let Some(ref m) = metrics {
+                                    // Update uptime before encoding
+                                    m.uptime_seconds.set(uptime_secs as i64);
+                                    m.connected_peers.set(connected_peers as i64);
+                                    m.record_connection_stats(&network.connection_stats());
+                                    (200, "text/plain; version=0.0.4; charset=utf-8", m.encode())
+                                } else {
+                                    // Metrics not enabled, return empty
+                                    (200, "text/plain", String::from("# Metrics not enabled\n"))
+                                }
+                            }
+                            "/health/live" => {
+                                (200, "application/json", String::from(r#"{"status":"ok"}"#))
+                            }
+                            "/health/ready" => {
+                                let report = health.report();
+                                let body = format!(r#"{{"status":"{}"}}"#, report.status);
+                                (report.http_status(), "application/json", body)
+                            }
+                            _ => {
+                                // Default to the full health report
+                                let mut report = health.report();
+                                report.connected_peers = connected_peers as u32;
+                                report.uptime_secs = uptime_secs;
+                                let body = serde_json::to_string(&report).unwrap_or_default();
+                                (report.http_status(), "application/json", body)
                             }
-                        } else {
-                            // Default to health endpoint
-                            let json = format!(
-                                r#"{{"status":"ok","connected_peers":{},"uptime_secs":{}}}"#,
-                                connected_peers, uptime_secs
-                            );
-                            ("application/json", json)
                         };
 
                         // Build HTTP response
+                        let reason = if status_code == 200 { "OK" } else { "Service Unavailable" };
                         let response = format!(
-                            "HTTP/1.1 200 OK\r\n\
+                            "HTTP/1.1 {} {}\r\n\
                              Content-Type: {}\r\n\
                              Content-Length: {}\r\n\
                              Connection: close\r\n\
                              \r\n\
                              {}",
+                            status_code,
+                            reason,
                             content_type,
                             body.len(),
                             body
@@ -848,6 +1275,7 @@ mod tests {
         let status = RuntimeStatus {
             connected_peers: 7,
             updated_at: 1700000000,
+            health: None,
         };
 
         write_status_file(&path, &status).unwrap();
@@ -857,6 +1285,17 @@ mod tests {
         assert_eq!(read_back.updated_at, 1700000000);
     }
 
+    #[test]
+    fn test_read_status_file_without_health() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("node.status");
+        std::fs::write(&path, r#"{"connected_peers":3,"updated_at":1700000000}"#).unwrap();
+
+        let read_back = read_status_file(&path).unwrap();
+        assert_eq!(read_back.connected_peers, 3);
+        assert!(read_back.health.is_none());
+    }
+
     #[test]
     fn test_remove_status_file() {
         let temp_dir = TempDir::new().unwrap();
@@ -865,6 +1304,7 @@ mod tests {
         let status = RuntimeStatus {
             connected_peers: 1,
             updated_at: 1700000000,
+            health: None,
         };
         write_status_file(&path, &status).unwrap();
         assert!(path.exists());
@@ -0,0 +1,367 @@
+//! Notifiers for earnings and activity digests.
+//!
+//! The ops layer decides when a digest is due (see `[notifications]`);
+//! the notifiers here deliver it by email and to webhooks. A desktop app
+//! embedding the node registers its own `Notifier` instead.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use nodalync_ops::{ActivityDigest, Notifier};
+use serde_json::json;
+
+use crate::config::{
+    format_hbar, EmailConfig, NotificationsConfig, SmtpTls, WebhookConfig, WebhookType,
+};
+use crate::error::{CliError, CliResult};
+
+/// Build the notifiers configured under `[notifications]`.
+pub fn build_notifiers(config: &NotificationsConfig) -> CliResult<Vec<Arc<dyn Notifier>>> {
+    let mut notifiers: Vec<Arc<dyn Notifier>> = Vec::new();
+    if let Some(email) = &config.email {
+        notifiers.push(Arc::new(EmailNotifier::new(email)?));
+    }
+    for webhook in &config.webhooks {
+        notifiers.push(Arc::new(WebhookNotifier::new(webhook.clone())));
+    }
+    Ok(notifiers)
+}
+
+/// Subject line for a digest.
+pub fn digest_subject(digest: &ActivityDigest) -> String {
+    format!(
+        "Nodalync {} digest for {}: {} earned",
+        digest.period,
+        period_label(digest),
+        format_hbar(digest.earnings)
+    )
+}
+
+/// Plain-text body of a digest.
+pub fn digest_text(digest: &ActivityDigest) -> String {
+    let mut lines = vec![
+        format!(
+            "Activity for {} ({} UTC)",
+            digest.node,
+            period_label(digest)
+        ),
+        String::new(),
+        format!("Earnings:       {}", format_hbar(digest.earnings)),
+        format!(
+            "Queries:        {} paid, {} free",
+            digest.paid_queries, digest.free_queries
+        ),
+        format!("Previews:       {}", digest.previews),
+        format!(
+            "Consumers:      {} ({} new)",
+            digest.consumers, digest.new_consumers
+        ),
+    ];
+
+    if !digest.top_content.is_empty() {
+        lines.push(String::new());
+        lines.push("Top content:".to_string());
+        for content in &digest.top_content {
+            let title = if content.title.is_empty() {
+                content.hash.to_string()
+            } else {
+                content.title.clone()
+            };
+            lines.push(format!(
+                "  {}  {} ({} queries)",
+                title,
+                format_hbar(content.revenue),
+                content.queries
+            ));
+        }
+    }
+
+    if !digest.failed_settlements.is_empty() {
+        lines.push(String::new());
+        lines.push(format!(
+            "Failed settlements ({}), still queued:",
+            digest.failed_settlements.len()
+        ));
+        for failure in &digest.failed_settlements {
+            lines.push(format!(
+                "  {}  {}  {}",
+                utc_date(failure.timestamp),
+                format_hbar(failure.amount),
+                failure.error
+            ));
+        }
+    }
+
+    if digest.is_empty() {
+        lines.push(String::new());
+        lines.push("No activity this period.".to_string());
+    }
+
+    lines.join("\n")
+}
+
+/// The period's dates, e.g. `2024-01-15` or `2024-01-15 to 2024-01-21`.
+fn period_label(digest: &ActivityDigest) -> String {
+    let first = utc_date(digest.start);
+    let last = utc_date(digest.end.saturating_sub(1));
+    if first == last {
+        first
+    } else {
+        format!("{} to {}", first, last)
+    }
+}
+
+/// Format a timestamp (ms) as a UTC date, `YYYY-MM-DD`.
+fn utc_date(timestamp: u64) -> String {
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let days = (timestamp / 86_400_000) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Sends digests by email over SMTP.
+pub struct EmailNotifier {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl EmailNotifier {
+    /// Create a notifier from the SMTP settings.
+    pub fn new(config: &EmailConfig) -> CliResult<Self> {
+        let invalid = |e: &dyn std::fmt::Display| {
+            CliError::config(format!("Invalid [notifications.email] setting: {}", e))
+        };
+
+        let builder = match config.tls {
+            SmtpTls::Starttls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.smtp_host)
+                    .map_err(|e| invalid(&e))?
+            }
+            SmtpTls::Wrapper => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.smtp_host)
+                .map_err(|e| invalid(&e))?,
+            SmtpTls::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.smtp_host)
+            }
+        };
+        let mut builder = builder
+            .port(config.smtp_port)
+            .timeout(Some(Duration::from_secs(30)));
+        if let Some(username) = &config.username {
+            builder = builder.credentials(Credentials::new(
+                username.clone(),
+                config.password.clone().unwrap_or_default(),
+            ));
+        }
+
+        let from = config.from.parse().map_err(|e| invalid(&e))?;
+        let to = config
+            .to
+            .iter()
+            .map(|address| address.parse().map_err(|e| invalid(&e)))
+            .collect::<CliResult<Vec<Mailbox>>>()?;
+        if to.is_empty() {
+            return Err(CliError::config(
+                "[notifications.email] needs at least one address in `to`",
+            ));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+            to,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for EmailNotifier {
+    fn name(&self) -> &str {
+        "email"
+    }
+
+    async fn notify(&self, digest: &ActivityDigest) -> Result<(), String> {
+        let mut message = Message::builder()
+            .from(self.from.clone())
+            .subject(digest_subject(digest))
+            .header(ContentType::TEXT_PLAIN);
+        for to in &self.to {
+            message = message.to(to.clone());
+        }
+        let message = message
+            .body(digest_text(digest))
+            .map_err(|e| e.to_string())?;
+
+        self.transport
+            .send(message)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Posts digests to a webhook.
+pub struct WebhookNotifier {
+    config: WebhookConfig,
+    client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    /// Create a notifier posting to the webhook.
+    pub fn new(config: WebhookConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .unwrap_or_default();
+        Self { config, client }
+    }
+
+    /// The body posted for a digest.
+    fn payload(&self, digest: &ActivityDigest) -> serde_json::Value {
+        let text = format!("*{}*\n{}", digest_subject(digest), digest_text(digest));
+        match self.config.webhook_type {
+            WebhookType::Slack => json!({ "text": text }),
+            WebhookType::Discord => json!({ "content": text }),
+            WebhookType::Generic | WebhookType::Pagerduty => json!(digest),
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn notify(&self, digest: &ActivityDigest) -> Result<(), String> {
+        let mut request = self
+            .client
+            .post(&self.config.url)
+            .json(&self.payload(digest));
+        if let Some(auth) = &self.config.auth_header {
+            request = request.header("Authorization", auth);
+        }
+
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("webhook returned status {}", response.status()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, PeerId};
+    use nodalync_ops::TopContent;
+    use nodalync_store::{DigestPeriod, SettlementFailure};
+
+    fn digest() -> ActivityDigest {
+        ActivityDigest {
+            period: DigestPeriod::Weekly,
+            start: 1_705_276_800_000, // Monday 2024-01-15
+            end: 1_705_881_600_000,
+            node: PeerId::from_bytes([1u8; 20]),
+            earnings: 150_000_000,
+            paid_queries: 12,
+            free_queries: 3,
+            previews: 20,
+            consumers: 7,
+            new_consumers: 2,
+            top_content: vec![TopContent {
+                hash: content_hash(b"guide"),
+                title: "Rust Guide".to_string(),
+                queries: 9,
+                revenue: 100_000_000,
+            }],
+            failed_settlements: vec![SettlementFailure {
+                batch_id: content_hash(b"batch"),
+                amount: 50_000_000,
+                error: "INSUFFICIENT_PAYER_BALANCE".to_string(),
+                timestamp: 1_705_400_000_000,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_utc_date() {
+        assert_eq!(utc_date(0), "1970-01-01");
+        assert_eq!(utc_date(951_782_400_000), "2000-02-29");
+        assert_eq!(utc_date(1_705_881_599_999), "2024-01-21");
+    }
+
+    #[test]
+    fn test_digest_text() {
+        let digest = digest();
+        assert_eq!(
+            digest_subject(&digest),
+            "Nodalync weekly digest for 2024-01-15 to 2024-01-21: 1.50 HBAR earned"
+        );
+
+        let text = digest_text(&digest);
+        assert!(text.contains("12 paid, 3 free"));
+        assert!(text.contains("7 (2 new)"));
+        assert!(text.contains("Rust Guide"));
+        assert!(text.contains("Failed settlements (1)"));
+        assert!(text.contains("INSUFFICIENT_PAYER_BALANCE"));
+        assert!(!text.contains("No activity"));
+    }
+
+    #[test]
+    fn test_webhook_payloads() {
+        let webhook = |webhook_type| {
+            WebhookNotifier::new(WebhookConfig {
+                url: "https://example.com/hook".to_string(),
+                webhook_type,
+                auth_header: None,
+                alert_types: vec![],
+                timeout_secs: 10,
+            })
+        };
+        let digest = digest();
+
+        let slack = webhook(WebhookType::Slack).payload(&digest);
+        assert!(slack["text"].as_str().unwrap().contains("weekly digest"));
+        let discord = webhook(WebhookType::Discord).payload(&digest);
+        assert!(discord["content"].is_string());
+        let generic = webhook(WebhookType::Generic).payload(&digest);
+        assert_eq!(generic["earnings"], 150_000_000);
+        assert_eq!(generic["period"], "weekly");
+    }
+
+    #[test]
+    fn test_build_notifiers() {
+        let mut config = NotificationsConfig::default();
+        assert!(build_notifiers(&config).unwrap().is_empty());
+
+        config.email = Some(EmailConfig {
+            smtp_host: "smtp.example.com".to_string(),
+            smtp_port: 587,
+            tls: SmtpTls::Starttls,
+            username: None,
+            password: None,
+            from: "node@example.com".to_string(),
+            to: vec!["me@example.com".to_string()],
+        });
+        let notifiers = build_notifiers(&config).unwrap();
+        assert_eq!(notifiers.len(), 1);
+        assert_eq!(notifiers[0].name(), "email");
+
+        config.email.as_mut().unwrap().to = vec!["not an address".to_string()];
+        assert!(build_notifiers(&config).is_err());
+    }
+}
@@ -1,13 +1,20 @@
 //! Output formatting for CLI.
 
 use colored::Colorize;
-use nodalync_types::{L1Summary, Manifest};
-use serde::Serialize;
+use nodalync_store::{InvoiceRecord, LibraryEntry, ModerationEntry, StoredGroup};
+use nodalync_types::{
+    AttributionCertificate, ContentSection, DidDocument, L1Summary, Manifest, PricingRule,
+    QuotaPolicy, ReferralPolicy, TrialPolicy,
+};
+use serde::{Deserialize, Serialize};
 
 use crate::config::format_ndl;
+use crate::health::{HealthReport, HealthState};
+use crate::logging::{self, LogEntry};
 
 /// Output format for CLI commands.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     /// Human-readable output.
     #[default]
@@ -63,16 +70,21 @@ pub trait Render {
 pub struct InitOutput {
     pub peer_id: String,
     pub config_path: String,
+    pub revocation_path: String,
 }
 
 impl Render for InitOutput {
     fn render_human(&self) -> String {
         format!(
-            "{} {}\n{} {}\n\n{}\n  {}  Publish content\n  {}  Check node status",
+            "{} {}\n{} {}\n{} {}\n  {}\n\n{}\n  {}  Publish content\n  {}  Check node status",
             "Identity created:".green().bold(),
             self.peer_id,
             "Configuration saved to:".green(),
             self.config_path,
+            "Revocation certificate:".green(),
+            self.revocation_path,
+            "Move it somewhere offline; `nodalync revoke` with it retires this key if it leaks."
+                .yellow(),
             "Next steps:".bold(),
             "nodalync publish <file> --title \"My Document\"".cyan(),
             "nodalync status".cyan(),
@@ -90,6 +102,7 @@ pub struct WhoamiOutput {
     pub peer_id: String,
     pub libp2p_peer_id: String,
     pub public_key: String,
+    pub did: String,
     pub addresses: Vec<String>,
 }
 
@@ -99,6 +112,7 @@ impl Render for WhoamiOutput {
             format!("{} {}", "PeerId:".bold(), self.peer_id),
             format!("{} {}", "Libp2p PeerId:".bold(), self.libp2p_peer_id),
             format!("{} {}", "Public Key:".bold(), self.public_key),
+            format!("{} {}", "DID:".bold(), self.did),
         ];
         if !self.addresses.is_empty() {
             lines.push(format!("{}", "Addresses:".bold()));
@@ -114,6 +128,84 @@ impl Render for WhoamiOutput {
     }
 }
 
+/// Output for did command.
+#[derive(Debug, Serialize)]
+pub struct DidOutput {
+    pub did: String,
+    pub peer_id: String,
+    /// File the DID document was written to, if exported.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub document: DidDocument,
+}
+
+impl Render for DidOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![
+            format!("{} {}", "DID:".bold(), self.did),
+            format!("{} {}", "PeerId:".bold(), self.peer_id),
+        ];
+        match &self.path {
+            Some(path) => lines.push(format!("{} {}", "DID document written to".green(), path)),
+            None => lines.push(serde_json::to_string_pretty(&self.document).unwrap_or_default()),
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for resolve-did and verify-did commands.
+#[derive(Debug, Serialize)]
+pub struct DidResolutionOutput {
+    /// Whether a DID document was verified, rather than a DID resolved.
+    pub verified: bool,
+    pub did: String,
+    pub peer_id: String,
+    pub public_key: String,
+    /// Whether the peer is in the local peer store.
+    pub known_peer: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub addresses: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reputation: Option<i64>,
+    pub document: DidDocument,
+}
+
+impl Render for DidResolutionOutput {
+    fn render_human(&self) -> String {
+        let header = if self.verified {
+            "DID document verified:"
+        } else {
+            "DID resolved:"
+        };
+        let mut lines = vec![
+            format!("{} {}", header.green().bold(), self.did),
+            format!("{} {}", "PeerId:".bold(), self.peer_id),
+            format!("{} {}", "Public Key:".bold(), self.public_key),
+        ];
+        match self.reputation {
+            Some(reputation) => {
+                lines.push(format!("{} {}", "Reputation:".bold(), reputation));
+            }
+            None => lines.push(format!("{}", "Peer not seen on this node".dimmed())),
+        }
+        if !self.addresses.is_empty() {
+            lines.push(format!("{}", "Addresses:".bold()));
+            for addr in &self.addresses {
+                lines.push(format!("  {}", addr));
+            }
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
 /// Output for publish command.
 #[derive(Debug, Serialize)]
 pub struct PublishOutput {
@@ -123,20 +215,97 @@ pub struct PublishOutput {
     pub price: u64,
     pub visibility: String,
     pub mentions: Option<usize>,
+    /// Scheduled publish time (Unix ms), if embargoed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publish_at: Option<u64>,
+    /// Tags the content was filed under (author's tags plus L1 topics).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 impl Render for PublishOutput {
     fn render_human(&self) -> String {
+        let header = if self.publish_at.is_some() {
+            "Scheduled:"
+        } else {
+            "Published:"
+        };
         let mut lines = vec![
-            format!("{} {}", "Published:".green().bold(), self.hash),
+            format!("{} {}", header.green().bold(), self.hash),
             format!("{} \"{}\"", "Title:".bold(), self.title),
             format!("{} {} bytes", "Size:".bold(), self.size),
             format!("{} {}", "Price:".bold(), format_ndl(self.price)),
             format!("{} {}", "Visibility:".bold(), self.visibility),
         ];
+        if let Some(publish_at) = self.publish_at {
+            lines.push(format!(
+                "{} {} UTC",
+                "Publish At:".bold(),
+                logging::format_timestamp(publish_at)
+            ));
+        }
         if let Some(count) = self.mentions {
             lines.push(format!("{} {} found", "L1 Mentions:".bold(), count));
         }
+        if !self.tags.is_empty() {
+            lines.push(format!("{} {}", "Tags:".bold(), self.tags.join(", ")));
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for `publish --suggest-price`.
+#[derive(Debug, Serialize)]
+pub struct PriceSuggestionOutput {
+    pub title: String,
+    /// `None` when the node knows no priced content to compare with.
+    pub suggestion: Option<nodalync_econ::PriceSuggestion>,
+    /// Price used when publishing without `--price`.
+    pub default_price: u64,
+}
+
+impl Render for PriceSuggestionOutput {
+    fn render_human(&self) -> String {
+        let Some(suggestion) = &self.suggestion else {
+            return format!(
+                "No priced content to compare \"{}\" with yet.\n{} {} (default)",
+                self.title,
+                "Price:".bold(),
+                format_ndl(self.default_price)
+            );
+        };
+
+        let mut lines = vec![
+            format!(
+                "{} {}",
+                "Suggested Price:".green().bold(),
+                format_ndl(suggestion.median)
+            ),
+            format!(
+                "{} {} - {}",
+                "Range:".bold(),
+                format_ndl(suggestion.low),
+                format_ndl(suggestion.high)
+            ),
+            format!(
+                "{} {} priced item(s) like \"{}\"",
+                "Based On:".bold(),
+                suggestion.comparables,
+                self.title
+            ),
+        ];
+        if let Some(rate) = suggestion.conversion_rate {
+            lines.push(format!(
+                "{} {:.1}% of previews of your similar content were bought (range x{:.2})",
+                "Demand:".bold(),
+                rate * 100.0,
+                suggestion.demand_factor
+            ));
+        }
         lines.join("\n")
     }
 
@@ -276,6 +445,13 @@ pub struct PreviewOutput {
     pub content_type: String,
     pub visibility: String,
     pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<serde_json::Value>,
+    /// Other versions the owner published with the same version number.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub forks: Vec<String>,
     pub mentions: Option<PreviewMentions>,
 }
 
@@ -297,6 +473,25 @@ impl Render for PreviewOutput {
             format!("{} {} bytes", "Size:".bold(), self.size),
         ];
 
+        if let Some(schema) = &self.schema {
+            lines.push(format!("{} {}", "Schema:".bold(), schema));
+        }
+        if let Some(serde_json::Value::Object(fields)) = &self.fields {
+            for (name, value) in fields {
+                lines.push(format!("  {}: {}", name, value));
+            }
+        }
+        if !self.forks.is_empty() {
+            lines.push(format!(
+                "{} {}",
+                "Forked:".bold(),
+                "the owner published other versions with this number".yellow()
+            ));
+            for fork in &self.forks {
+                lines.push(format!("  - {}", fork));
+            }
+        }
+
         if let Some(mentions) = &self.mentions {
             lines.push(String::new());
             lines.push(format!(
@@ -323,18 +518,78 @@ impl Render for PreviewOutput {
 pub struct QueryOutput {
     pub hash: String,
     pub title: String,
+    /// Title of the section bought, when only one was.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    /// Units read, for a metered read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metered: Option<MeteredSummary>,
+    /// Free reads left, for a trial read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trial: Option<TrialSummary>,
     pub price_paid: u64,
     pub saved_to: String,
 }
 
+/// What a free trial read got.
+#[derive(Debug, Serialize)]
+pub struct TrialSummary {
+    /// Free reads left (None for own content).
+    pub reads_left: Option<u32>,
+    /// Whether the whole content was read, not just its free bytes.
+    pub complete: bool,
+}
+
+/// How much of metered content a query read.
+#[derive(Debug, Serialize)]
+pub struct MeteredSummary {
+    pub units: u32,
+    pub total_units: u32,
+    /// Price of the whole content.
+    pub full_price: u64,
+}
+
 impl Render for QueryOutput {
     fn render_human(&self) -> String {
+        let section = self
+            .section
+            .as_ref()
+            .map(|title| format!("\n{} {}", "Section:".bold(), title))
+            .unwrap_or_default();
+        let metered = self
+            .metered
+            .as_ref()
+            .map(|m| {
+                format!(
+                    "\n{} {} of {} (whole content {})",
+                    "Units:".bold(),
+                    m.units,
+                    m.total_units,
+                    format_ndl(m.full_price)
+                )
+            })
+            .unwrap_or_default();
+        let trial = self
+            .trial
+            .as_ref()
+            .map(|t| {
+                let left = t
+                    .reads_left
+                    .map(|left| format!(", {} left", left))
+                    .unwrap_or_default();
+                let part = if t.complete { "" } else { " (free bytes only)" };
+                format!("\n{} free read{}{}", "Trial:".bold(), left, part)
+            })
+            .unwrap_or_default();
         format!(
-            "{} {}\n{} {}\n{} {}\n{} {}",
+            "{} {}\n{} {}{}{}{}\n{} {}\n{} {}",
             "Queried:".green().bold(),
             self.hash,
             "Title:".bold(),
             self.title,
+            section,
+            metered,
+            trial,
             "Payment:".bold(),
             format_ndl(self.price_paid),
             "Saved to:".bold(),
@@ -440,47 +695,29 @@ impl Render for TransactionOutput {
     }
 }
 
-/// Output for versions command.
-#[derive(Debug, Serialize)]
-pub struct VersionsOutput {
-    pub version_root: String,
-    pub versions: Vec<VersionInfo>,
-}
-
+/// Output for post-bond and bond commands.
 #[derive(Debug, Serialize)]
-pub struct VersionInfo {
-    pub version: u32,
-    pub hash: String,
-    pub timestamp: u64,
-    pub visibility: String,
-    pub is_latest: bool,
+pub struct BondOutput {
+    /// Total bond posted on-chain.
+    pub amount: u64,
+    /// Transaction that last added to the bond.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_id: Option<String>,
 }
 
-impl Render for VersionsOutput {
+impl Render for BondOutput {
     fn render_human(&self) -> String {
+        if self.amount == 0 {
+            return "No publisher bond posted.".dimmed().to_string();
+        }
         let mut lines = vec![format!(
             "{} {}",
-            "Version root:".bold(),
-            short_hash(&self.version_root)
+            "Publisher Bond:".bold(),
+            format_ndl(self.amount)
         )];
-
-        for v in &self.versions {
-            let latest_marker = if v.is_latest {
-                " [latest]".green()
-            } else {
-                "".into()
-            };
-            let timestamp = format_timestamp(v.timestamp);
-            lines.push(format!(
-                "  v{}: {} ({}) - {}{}",
-                v.version,
-                short_hash(&v.hash).cyan(),
-                timestamp,
-                v.visibility.to_lowercase(),
-                latest_marker
-            ));
+        if let Some(tx_id) = &self.transaction_id {
+            lines.push(format!("{} {}", "Transaction:".bold(), tx_id));
         }
-
         lines.join("\n")
     }
 
@@ -489,51 +726,42 @@ impl Render for VersionsOutput {
     }
 }
 
-/// Output for visibility command.
+/// Output for the retention status command.
 #[derive(Debug, Serialize)]
-pub struct VisibilityOutput {
-    pub hash: String,
-    pub new_visibility: String,
-}
-
-impl Render for VisibilityOutput {
-    fn render_human(&self) -> String {
-        format!(
-            "{} {} -> {}",
-            "Visibility updated:".green().bold(),
-            short_hash(&self.hash),
-            self.new_visibility
-        )
-    }
-
-    fn render_json(&self) -> String {
-        serde_json::to_string_pretty(self).unwrap_or_default()
-    }
+pub struct RetentionStatusOutput {
+    pub categories: Vec<RetentionCategoryStatus>,
 }
 
-/// Output for update command.
 #[derive(Debug, Serialize)]
-pub struct UpdateOutput {
-    pub previous_hash: String,
-    pub previous_version: u32,
-    pub new_hash: String,
-    pub new_version: u32,
-    pub version_root: String,
+pub struct RetentionCategoryStatus {
+    pub category: String,
+    /// Retention limit in days (`None` = kept forever).
+    pub ttl_days: Option<u64>,
+    pub records: u64,
+    /// Records past the limit, deleted at the next enforcement.
+    pub expired: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oldest: Option<u64>,
 }
 
-impl Render for UpdateOutput {
+impl Render for RetentionStatusOutput {
     fn render_human(&self) -> String {
-        format!(
-            "{} {} (v{})\n{} {} (v{})\n{} {}",
-            "Previous:".bold(),
-            short_hash(&self.previous_hash),
-            self.previous_version,
-            "New:".green().bold(),
-            short_hash(&self.new_hash),
-            self.new_version,
-            "Version root:".bold(),
-            short_hash(&self.version_root)
-        )
+        let mut lines = vec![format!("{}", "Data Retention".bold())];
+        for c in &self.categories {
+            let ttl = match c.ttl_days {
+                Some(days) => format!("keep {} days", days),
+                None => "keep forever".to_string(),
+            };
+            let mut line = format!("  {:<16} {:>8} records  {:<16}", c.category, c.records, ttl);
+            if let Some(oldest) = c.oldest {
+                line.push_str(&format!(" oldest {}", format_timestamp(oldest)));
+            }
+            if c.expired > 0 {
+                line.push_str(&format!("  ({} expired)", c.expired).yellow().to_string());
+            }
+            lines.push(line);
+        }
+        lines.join("\n")
     }
 
     fn render_json(&self) -> String {
@@ -541,37 +769,50 @@ impl Render for UpdateOutput {
     }
 }
 
-/// Output for synthesize command.
+/// Output for the digest command.
 #[derive(Debug, Serialize)]
-pub struct SynthesizeOutput {
-    pub hash: String,
-    pub source_count: usize,
-    pub provenance_roots: usize,
-    pub published: bool,
-    pub price: Option<u64>,
+pub struct DigestOutput {
+    pub subject: String,
+    /// Plain-text digest, as emailed.
+    #[serde(skip)]
+    pub text: String,
+    pub digest: nodalync_ops::ActivityDigest,
+    /// Notifiers that accepted the digest, with `--send`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sent: Vec<String>,
+    /// Notifiers that failed, with their errors.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failed: Vec<NotifierFailure>,
 }
 
-impl Render for SynthesizeOutput {
+/// A notifier that failed to deliver a digest.
+#[derive(Debug, Serialize)]
+pub struct NotifierFailure {
+    pub notifier: String,
+    pub error: String,
+}
+
+impl Render for DigestOutput {
     fn render_human(&self) -> String {
         let mut lines = vec![
-            format!("{} {}", "L3 hash:".green().bold(), self.hash),
-            format!("{} {}", "Sources:".bold(), self.source_count),
-            format!("{} {}", "Provenance roots:".bold(), self.provenance_roots),
+            self.subject.bold().to_string(),
+            String::new(),
+            self.text.clone(),
         ];
-
-        if self.published {
-            if let Some(price) = self.price {
-                lines.push(format!(
-                    "{} {} ({})",
-                    "Published:".bold(),
-                    "Yes".green(),
-                    format_ndl(price)
-                ));
-            } else {
-                lines.push(format!("{} {}", "Published:".bold(), "Yes".green()));
-            }
+        if !self.sent.is_empty() || !self.failed.is_empty() {
+            lines.push(String::new());
+        }
+        for notifier in &self.sent {
+            lines.push(format!("{} {}", "Sent via".green(), notifier));
+        }
+        for failure in &self.failed {
+            lines.push(format!(
+                "{} {}: {}",
+                "Failed via".red(),
+                failure.notifier,
+                failure.error
+            ));
         }
-
         lines.join("\n")
     }
 
@@ -580,28 +821,63 @@ impl Render for SynthesizeOutput {
     }
 }
 
-/// Output for build-l2 command.
+/// Output for the webhooks list command.
 #[derive(Debug, Serialize)]
-pub struct BuildL2Output {
-    pub hash: String,
-    pub entity_count: usize,
-    pub relationship_count: usize,
-    pub source_count: usize,
+pub struct WebhooksOutput {
+    /// Endpoints registered in the configuration.
+    pub endpoints: usize,
+    pub deliveries: Vec<WebhookDeliveryInfo>,
 }
 
-impl Render for BuildL2Output {
+#[derive(Debug, Serialize)]
+pub struct WebhookDeliveryInfo {
+    pub id: u64,
+    pub url: String,
+    pub event: String,
+    pub status: String,
+    pub attempts: u32,
+    pub created_at: u64,
+    /// When the next attempt is due, for pending deliveries.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_attempt_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl Render for WebhooksOutput {
     fn render_human(&self) -> String {
-        format!(
-            "{} {}\n{} {}\n{} {}\n{} {}",
-            "L2 Entity Graph:".green().bold(),
-            self.hash,
-            "Entities:".bold(),
-            self.entity_count,
-            "Relationships:".bold(),
-            self.relationship_count,
-            "Sources:".bold(),
-            self.source_count
-        )
+        let mut lines = vec![format!(
+            "{} ({} endpoints registered)",
+            "Webhook Deliveries".bold(),
+            self.endpoints
+        )];
+        if self.deliveries.is_empty() {
+            lines.push("  No webhook deliveries".to_string());
+        }
+        for d in &self.deliveries {
+            let status = match d.status.as_str() {
+                "delivered" => d.status.green(),
+                "failed" => d.status.red(),
+                _ => d.status.yellow(),
+            };
+            let mut line = format!(
+                "  #{:<6} {} {:<20} {:<9} {} attempts  {}",
+                d.id,
+                format_timestamp(d.created_at),
+                d.event,
+                status,
+                d.attempts,
+                d.url
+            );
+            if let Some(next) = d.next_attempt_at {
+                line.push_str(&format!("  next {}", format_timestamp(next)));
+            }
+            lines.push(line);
+            if let Some(error) = &d.last_error {
+                lines.push(format!("          {}", error.dimmed()));
+            }
+        }
+        lines.join("\n")
     }
 
     fn render_json(&self) -> String {
@@ -609,27 +885,2292 @@ impl Render for BuildL2Output {
     }
 }
 
-/// Output for merge-l2 command.
+/// Output for standing-orders list and add commands.
 #[derive(Debug, Serialize)]
-pub struct MergeL2Output {
-    pub hash: String,
-    pub merged_count: usize,
-    pub entity_count: usize,
-    pub relationship_count: usize,
+pub struct StandingOrdersOutput {
+    /// Whether a running node runs the orders.
+    pub enabled: bool,
+    /// Order just added, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub added: Option<u64>,
+    pub orders: Vec<StandingOrderInfo>,
 }
 
-impl Render for MergeL2Output {
+#[derive(Debug, Serialize)]
+pub struct StandingOrderInfo {
+    pub id: u64,
+    pub tag: String,
+    pub max_price: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_reputation: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<u64>,
+    pub spent: u64,
+    pub created_at: u64,
+}
+
+impl Render for StandingOrdersOutput {
+    fn render_human(&self) -> String {
+        let mut lines = Vec::new();
+        if let Some(id) = self.added {
+            lines.push(format!("{} #{}", "Standing order added:".green(), id));
+        }
+        lines.push(format!("{}", "Standing Orders".bold()));
+        if !self.enabled {
+            lines.push(
+                "  Not running; set enabled under [standing_orders] in config.toml"
+                    .yellow()
+                    .to_string(),
+            );
+        }
+        if self.orders.is_empty() {
+            lines.push("  No standing orders".to_string());
+        }
+        for o in &self.orders {
+            let mut line = format!(
+                "  #{:<6} {:<24} up to {}",
+                o.id,
+                o.tag,
+                format_ndl(o.max_price)
+            );
+            if let Some(min) = o.min_reputation {
+                line.push_str(&format!("  reputation >= {}", min));
+            }
+            match o.budget {
+                Some(budget) => line.push_str(&format!(
+                    "  spent {} of {}",
+                    format_ndl(o.spent),
+                    format_ndl(budget)
+                )),
+                None => line.push_str(&format!("  spent {}", format_ndl(o.spent))),
+            }
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for standing-orders remove command.
+#[derive(Debug, Serialize)]
+pub struct StandingOrderRemoveOutput {
+    pub id: u64,
+    pub removed: bool,
+}
+
+impl Render for StandingOrderRemoveOutput {
+    fn render_human(&self) -> String {
+        if self.removed {
+            format!("{} #{}", "Standing order removed:".green(), self.id)
+        } else {
+            format!("{} #{}", "No such standing order:".yellow(), self.id)
+        }
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for standing-orders purchases command.
+#[derive(Debug, Serialize)]
+pub struct StandingOrderPurchasesOutput {
+    pub purchases: Vec<StandingOrderPurchaseInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StandingOrderPurchaseInfo {
+    pub hash: String,
+    pub order_id: u64,
+    pub title: String,
+    pub amount: u64,
+    pub purchased_at: u64,
+}
+
+impl Render for StandingOrderPurchasesOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![format!("{}", "Standing Order Purchases".bold())];
+        if self.purchases.is_empty() {
+            lines.push("  No purchases".to_string());
+        }
+        for p in &self.purchases {
+            lines.push(format!(
+                "  {} #{:<6} {} {:<12} {}",
+                format_timestamp(p.purchased_at),
+                p.order_id,
+                short_hash(&p.hash),
+                format_ndl(p.amount),
+                p.title
+            ));
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TenantInfo {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<u64>,
+    pub spent: u64,
+    pub created_at: u64,
+}
+
+impl TenantInfo {
+    fn spending(&self) -> String {
+        match self.budget {
+            Some(budget) => format!("spent {} of {}", format_ndl(self.spent), format_ndl(budget)),
+            None => format!("spent {}", format_ndl(self.spent)),
+        }
+    }
+}
+
+/// Output for tenants list command.
+#[derive(Debug, Serialize)]
+pub struct TenantsOutput {
+    pub tenants: Vec<TenantInfo>,
+}
+
+impl Render for TenantsOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![format!("{}", "Tenants".bold())];
+        if self.tenants.is_empty() {
+            lines.push("  No tenants".to_string());
+        }
+        for t in &self.tenants {
+            lines.push(format!("  {:<20} {:<24} {}", t.id, t.name, t.spending()));
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for tenants create and rotate-key commands.
+#[derive(Debug, Serialize)]
+pub struct TenantKeyOutput {
+    pub tenant: TenantInfo,
+    pub api_key: String,
+    /// Whether the tenant was just created (otherwise its key was rotated).
+    pub created: bool,
+}
+
+impl Render for TenantKeyOutput {
+    fn render_human(&self) -> String {
+        let heading = if self.created {
+            "Tenant created:"
+        } else {
+            "Tenant key rotated:"
+        };
+        [
+            format!("{} {}", heading.green(), self.tenant.id),
+            format!("  API key: {}", self.api_key),
+            "  Store it now; it is not shown again".yellow().to_string(),
+        ]
+        .join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for tenants show and budget commands.
+#[derive(Debug, Serialize)]
+pub struct TenantReportOutput {
+    pub tenant: TenantInfo,
+    pub purchases: usize,
+    pub content: Vec<String>,
+    pub earned: u64,
+}
+
+impl Render for TenantReportOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![
+            format!("{} {}", "Tenant".bold(), self.tenant.id),
+            format!("  Name:      {}", self.tenant.name),
+            format!("  Budget:    {}", self.tenant.spending()),
+            format!("  Purchases: {}", self.purchases),
+            format!("  Earned:    {}", format_ndl(self.earned)),
+            format!("  Content:   {}", self.content.len()),
+        ];
+        for hash in &self.content {
+            lines.push(format!("    {}", short_hash(hash)));
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for tenants remove command.
+#[derive(Debug, Serialize)]
+pub struct TenantRemoveOutput {
+    pub id: String,
+    pub removed: bool,
+}
+
+impl Render for TenantRemoveOutput {
+    fn render_human(&self) -> String {
+        if self.removed {
+            format!("{} {}", "Tenant removed:".green(), self.id)
+        } else {
+            format!("{} {}", "No such tenant:".yellow(), self.id)
+        }
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for tenants assign command.
+#[derive(Debug, Serialize)]
+pub struct TenantAssignOutput {
+    pub hash: String,
+    pub tenant: Option<String>,
+}
+
+impl Render for TenantAssignOutput {
+    fn render_human(&self) -> String {
+        match self.tenant {
+            Some(ref tenant) => format!(
+                "{} {} -> {}",
+                "Content assigned:".green(),
+                short_hash(&self.hash),
+                tenant
+            ),
+            None => format!(
+                "{} {}",
+                "Content unassigned:".green(),
+                short_hash(&self.hash)
+            ),
+        }
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for tenants purchases command.
+#[derive(Debug, Serialize)]
+pub struct TenantPurchasesOutput {
+    pub purchases: Vec<TenantPurchaseInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TenantPurchaseInfo {
+    pub payment_id: String,
+    pub tenant: String,
+    pub hash: String,
+    pub amount: u64,
+    pub purchased_at: u64,
+}
+
+impl Render for TenantPurchasesOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![format!("{}", "Tenant Purchases".bold())];
+        if self.purchases.is_empty() {
+            lines.push("  No purchases".to_string());
+        }
+        for p in &self.purchases {
+            lines.push(format!(
+                "  {} {:<20} {} {}",
+                format_timestamp(p.purchased_at),
+                p.tenant,
+                short_hash(&p.hash),
+                format_ndl(p.amount)
+            ));
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for versions command.
+#[derive(Debug, Serialize)]
+pub struct VersionsOutput {
+    pub version_root: String,
+    pub versions: Vec<VersionInfo>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub gaps: Vec<VersionGapInfo>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub forks: Vec<VersionForkInfo>,
+    /// Manifests fetched from the owner, with `--repair`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repaired: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionInfo {
+    pub version: u32,
+    pub hash: String,
+    pub timestamp: u64,
+    pub visibility: String,
+    pub is_latest: bool,
+}
+
+/// A version whose predecessor is missing.
+#[derive(Debug, Serialize)]
+pub struct VersionGapInfo {
+    pub missing: String,
+    pub before_version: u32,
+}
+
+/// Versions branching off the same predecessor.
+#[derive(Debug, Serialize)]
+pub struct VersionForkInfo {
+    pub previous: String,
+    pub branches: Vec<String>,
+}
+
+impl Render for VersionsOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![format!(
+            "{} {}",
+            "Version root:".bold(),
+            short_hash(&self.version_root)
+        )];
+
+        for v in &self.versions {
+            let latest_marker = if v.is_latest {
+                " [latest]".green()
+            } else {
+                "".into()
+            };
+            let timestamp = format_timestamp(v.timestamp);
+            lines.push(format!(
+                "  v{}: {} ({}) - {}{}",
+                v.version,
+                short_hash(&v.hash).cyan(),
+                timestamp,
+                v.visibility.to_lowercase(),
+                latest_marker
+            ));
+        }
+
+        for gap in &self.gaps {
+            lines.push(
+                format!(
+                    "  Missing version before v{}: {}",
+                    gap.before_version,
+                    short_hash(&gap.missing)
+                )
+                .yellow()
+                .to_string(),
+            );
+        }
+        for fork in &self.forks {
+            let branches: Vec<String> = fork.branches.iter().map(|h| short_hash(h)).collect();
+            lines.push(
+                format!(
+                    "  Fork after {}: {}",
+                    short_hash(&fork.previous),
+                    branches.join(", ")
+                )
+                .yellow()
+                .to_string(),
+            );
+        }
+        match self.repaired {
+            Some(fetched) => lines.push(format!(
+                "{} fetched {} missing version(s)",
+                "Repair:".bold(),
+                fetched
+            )),
+            None if !self.gaps.is_empty() => {
+                lines.push("Run with --repair to fetch missing versions from the owner".into())
+            }
+            None => {}
+        }
+
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for tags command.
+#[derive(Debug, Serialize)]
+pub struct TagsOutput {
+    /// Prefix being completed, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    pub tags: Vec<TagSummary>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TagSummary {
+    pub tag: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    pub content_count: u64,
+}
+
+impl Render for TagsOutput {
+    fn render_human(&self) -> String {
+        if self.tags.is_empty() {
+            return match &self.prefix {
+                Some(prefix) => format!("No tags matching \"{}\".", prefix),
+                None => "No tags yet. Add some with 'nodalync publish --tag'.".to_string(),
+            };
+        }
+
+        self.tags
+            .iter()
+            .map(|t| {
+                let noun = if t.content_count == 1 {
+                    "item"
+                } else {
+                    "items"
+                };
+                format!("  {} ({} {})", t.tag.cyan(), t.content_count, noun)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for storefront command.
+#[derive(Debug, Serialize)]
+pub struct StorefrontOutput {
+    pub publisher: String,
+    pub name: String,
+    pub created_at: u64,
+    pub categories: Vec<String>,
+    /// Listed items, featured first (only the chosen category if filtered).
+    pub items: Vec<StorefrontItemInfo>,
+    /// Whether the storefront was just stored in the DHT.
+    pub published: bool,
+}
+
+/// A listing in a storefront.
+#[derive(Debug, Serialize)]
+pub struct StorefrontItemInfo {
+    pub hash: String,
+    pub title: String,
+    pub content_type: String,
+    pub price: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    pub featured: bool,
+}
+
+impl Render for StorefrontOutput {
+    fn render_human(&self) -> String {
+        let name = if self.name.is_empty() {
+            "Storefront"
+        } else {
+            &self.name
+        };
+        let mut lines = vec![format!(
+            "{} {} ({}), {}",
+            "Storefront:".bold(),
+            name,
+            short_peer_id(&self.publisher),
+            format_timestamp(self.created_at)
+        )];
+        if self.published {
+            lines.push("  Published to the DHT".green().to_string());
+        }
+        if !self.categories.is_empty() {
+            lines.push(format!("  Categories: {}", self.categories.join(", ")));
+        }
+        if self.items.is_empty() {
+            lines.push("  No items".dimmed().to_string());
+        }
+        for item in &self.items {
+            let marker = if item.featured { "*" } else { " " };
+            let category = item
+                .category
+                .as_deref()
+                .map(|c| format!(" [{}]", c))
+                .unwrap_or_default();
+            lines.push(format!(
+                " {} {} ({}) {} {}{}",
+                marker.yellow(),
+                item.title,
+                short_hash(&item.hash),
+                item.content_type,
+                format_ndl(item.price),
+                category.dimmed()
+            ));
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for library command.
+#[derive(Debug, Serialize)]
+pub struct LibraryOutput {
+    /// Text titles were searched for, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    /// Purchases, most recent first.
+    pub entries: Vec<LibrarySummary>,
+    /// Total paid for the listed purchases.
+    pub total_paid: u64,
+}
+
+/// A purchase in the library.
+#[derive(Debug, Serialize)]
+pub struct LibrarySummary {
+    pub hash: String,
+    pub title: String,
+    pub content_type: String,
+    pub price_paid: u64,
+    pub provider: String,
+    pub purchased_at: u64,
+    pub payment_id: String,
+}
+
+/// Convert a library entry for display.
+pub fn library_to_summary(entry: &LibraryEntry) -> LibrarySummary {
+    LibrarySummary {
+        hash: entry.hash.to_string(),
+        title: entry.title.clone(),
+        content_type: format!("{:?}", entry.content_type),
+        price_paid: entry.price_paid,
+        provider: nodalync_crypto::peer_id_to_string(&entry.provider),
+        purchased_at: entry.purchased_at,
+        payment_id: entry.receipt.payment_id.to_string(),
+    }
+}
+
+impl Render for LibraryOutput {
+    fn render_human(&self) -> String {
+        if self.entries.is_empty() {
+            return match &self.search {
+                Some(text) => format!("No purchases matching \"{}\".", text)
+                    .dimmed()
+                    .to_string(),
+                None => "No purchases yet.".dimmed().to_string(),
+            };
+        }
+
+        let mut lines = vec![format!(
+            "{} {} purchase{}, {} paid",
+            "Library:".bold(),
+            self.entries.len(),
+            if self.entries.len() == 1 { "" } else { "s" },
+            format_ndl(self.total_paid)
+        )];
+        for entry in &self.entries {
+            lines.push(format!(
+                "  {} {} ({}) {} from {}  {}",
+                format_timestamp(entry.purchased_at),
+                truncate_title(&entry.title, 40),
+                short_hash(&entry.hash),
+                format_ndl(entry.price_paid),
+                short_peer_id(&entry.provider),
+                entry.content_type.dimmed()
+            ));
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for library --open.
+#[derive(Debug, Serialize)]
+pub struct LibraryOpenOutput {
+    pub entry: LibrarySummary,
+    pub saved_to: String,
+    /// Whether it was evicted and fetched again from its provider.
+    pub redownloaded: bool,
+}
+
+impl Render for LibraryOpenOutput {
+    fn render_human(&self) -> String {
+        let label = if self.redownloaded {
+            "Re-downloaded:"
+        } else {
+            "Opened:"
+        };
+        format!(
+            "{} {}\n{} {}\n{} {} on {}\n{} {}",
+            label.green().bold(),
+            self.entry.hash,
+            "Title:".bold(),
+            self.entry.title,
+            "Bought for:".bold(),
+            format_ndl(self.entry.price_paid),
+            format_timestamp(self.entry.purchased_at),
+            "Saved to:".bold(),
+            self.saved_to
+        )
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for visibility command.
+#[derive(Debug, Serialize)]
+pub struct VisibilityOutput {
+    pub hash: String,
+    pub new_visibility: String,
+    /// Groups whose members are admitted, if set.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<String>,
+}
+
+impl Render for VisibilityOutput {
+    fn render_human(&self) -> String {
+        let mut output = format!(
+            "{} {} -> {}",
+            "Visibility updated:".green().bold(),
+            short_hash(&self.hash),
+            self.new_visibility
+        );
+        if !self.groups.is_empty() {
+            let groups: Vec<_> = self.groups.iter().map(|g| short_hash(g)).collect();
+            output.push_str(&format!("\n{} {}", "Groups:".bold(), groups.join(", ")));
+        }
+        output
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for preview-policy command.
+#[derive(Debug, Serialize)]
+pub struct PreviewPolicyOutput {
+    pub hash: String,
+    pub redact_patterns: Vec<String>,
+    pub redact_sections: Vec<String>,
+    /// Summary text peers see before paying.
+    pub summary: String,
+    /// Mentions peers see before paying.
+    pub preview_mentions: Vec<String>,
+}
+
+impl Render for PreviewPolicyOutput {
+    fn render_human(&self) -> String {
+        let mut lines = Vec::new();
+        if self.redact_patterns.is_empty() && self.redact_sections.is_empty() {
+            lines.push(format!(
+                "{} {}",
+                "No preview policy:".bold(),
+                short_hash(&self.hash)
+            ));
+        } else {
+            lines.push(format!(
+                "{} {}",
+                "Preview policy:".green().bold(),
+                short_hash(&self.hash)
+            ));
+            for pattern in &self.redact_patterns {
+                lines.push(format!("  Redact pattern: {}", pattern));
+            }
+            for section in &self.redact_sections {
+                lines.push(format!("  Redact section: {}", section));
+            }
+        }
+
+        lines.push(String::new());
+        lines.push(format!("{} {}", "Peers see:".bold(), self.summary));
+        for mention in &self.preview_mentions {
+            lines.push(format!("  - {}", mention));
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for pricing command.
+#[derive(Debug, Serialize)]
+pub struct PricingOutput {
+    pub hash: String,
+    pub title: String,
+    /// Price the rule moves up from.
+    pub base_price: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<PricingRule>,
+    /// Paid queries in the surge window setting the current price.
+    pub window_queries: u64,
+    /// Price charged right now.
+    pub current_price: u64,
+}
+
+impl Render for PricingOutput {
+    fn render_human(&self) -> String {
+        let mut lines = Vec::new();
+        match &self.rule {
+            None => lines.push(format!(
+                "{} {} ({})",
+                "Fixed price:".bold(),
+                self.title,
+                short_hash(&self.hash)
+            )),
+            Some(rule) => {
+                lines.push(format!(
+                    "{} {} ({})",
+                    "Dynamic price:".green().bold(),
+                    self.title,
+                    short_hash(&self.hash)
+                ));
+                match rule {
+                    PricingRule::Surge {
+                        max_price,
+                        window_ms,
+                        step_bps,
+                    } => lines.push(format!(
+                        "  Surge: +{:.2}% per paid query in the previous {} min, up to {}",
+                        *step_bps as f64 / 100.0,
+                        window_ms / 60_000,
+                        format_ndl(*max_price)
+                    )),
+                    PricingRule::Decay {
+                        start_price,
+                        duration_ms,
+                        ..
+                    } => lines.push(format!(
+                        "  Dutch auction: from {} down over {:.1} hours",
+                        format_ndl(*start_price),
+                        *duration_ms as f64 / 3_600_000.0
+                    )),
+                }
+            }
+        }
+
+        lines.push(format!(
+            "{} {}",
+            "Base Price:".bold(),
+            format_ndl(self.base_price)
+        ));
+        if matches!(self.rule, Some(PricingRule::Surge { .. })) {
+            lines.push(format!(
+                "{} {}",
+                "Paid Queries (last window):".bold(),
+                self.window_queries
+            ));
+        }
+        lines.push(format!(
+            "{} {}",
+            "Current Price:".bold(),
+            format_ndl(self.current_price)
+        ));
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for sections command.
+#[derive(Debug, Serialize)]
+pub struct SectionsOutput {
+    pub hash: String,
+    pub title: String,
+    /// Price of the whole content.
+    pub price: u64,
+    pub sections: Vec<ContentSection>,
+}
+
+impl Render for SectionsOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![format!(
+            "{} {} ({}), whole content {}",
+            "Sections:".bold(),
+            self.title,
+            short_hash(&self.hash),
+            format_ndl(self.price)
+        )];
+        if self.sections.is_empty() {
+            lines.push("  Sold whole only".dimmed().to_string());
+        }
+        for (index, section) in self.sections.iter().enumerate() {
+            lines.push(format!(
+                "  [{}] {} - {} ({} bytes)",
+                index,
+                section.title,
+                format_ndl(section.price),
+                section.length
+            ));
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for metering command.
+#[derive(Debug, Serialize)]
+pub struct MeteringOutput {
+    pub hash: String,
+    pub title: String,
+    /// Price of the whole content.
+    pub price: u64,
+    /// Unit size in bytes, if the content is metered.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit_size: Option<u64>,
+    pub units: u32,
+    /// Price of the first unit; the last also takes any remainder.
+    pub unit_price: u64,
+}
+
+impl Render for MeteringOutput {
+    fn render_human(&self) -> String {
+        let header = format!(
+            "{} {} ({}), whole content {}",
+            "Metering:".bold(),
+            self.title,
+            short_hash(&self.hash),
+            format_ndl(self.price)
+        );
+        match self.unit_size {
+            Some(unit_size) => format!(
+                "{}\n  {} units of {} bytes, {} each",
+                header,
+                self.units,
+                unit_size,
+                format_ndl(self.unit_price)
+            ),
+            None => format!("{}\n  {}", header, "Sold whole only".dimmed()),
+        }
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for trial command.
+#[derive(Debug, Serialize)]
+pub struct TrialOutput {
+    pub hash: String,
+    pub title: String,
+    /// Price of the whole content.
+    pub price: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trial: Option<TrialPolicy>,
+    /// Peers that have had free reads.
+    pub readers: u32,
+}
+
+impl Render for TrialOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![format!(
+            "{} {} ({}), whole content {}",
+            "Trial:".bold(),
+            self.title,
+            short_hash(&self.hash),
+            format_ndl(self.price)
+        )];
+        match &self.trial {
+            Some(trial) => {
+                let part = trial
+                    .bytes
+                    .map(|bytes| format!(" of the first {} bytes", bytes))
+                    .unwrap_or_default();
+                lines.push(format!("  {} free reads per peer{}", trial.reads, part));
+            }
+            None => lines.push("  No free reads".dimmed().to_string()),
+        }
+        lines.push(format!("  {} peers have read it free", self.readers));
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for quota command.
+#[derive(Debug, Serialize)]
+pub struct QuotaOutput {
+    pub hash: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quota: Option<QuotaPolicy>,
+    /// Quota across all content, from the `[ops.quota]` config.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_quota: Option<QuotaPolicy>,
+    /// Peers' usage of the content today.
+    pub usage: Vec<QuotaUsageInfo>,
+}
+
+/// A peer's usage of content today.
+#[derive(Debug, Serialize)]
+pub struct QuotaUsageInfo {
+    pub peer_id: String,
+    pub queries: u32,
+    pub bytes: u64,
+}
+
+fn describe_quota(quota: &QuotaPolicy) -> String {
+    let mut limits = Vec::new();
+    if let Some(queries) = quota.queries {
+        limits.push(format!("{} queries", queries));
+    }
+    if let Some(bytes) = quota.bytes {
+        limits.push(format!("{} bytes", bytes));
+    }
+    format!("{} per peer per day", limits.join(" and "))
+}
+
+impl Render for QuotaOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![format!(
+            "{} {} ({})",
+            "Quota:".bold(),
+            self.title,
+            short_hash(&self.hash)
+        )];
+        match &self.quota {
+            Some(quota) => lines.push(format!("  {}", describe_quota(quota))),
+            None => lines.push("  No quota".dimmed().to_string()),
+        }
+        if let Some(quota) = &self.node_quota {
+            lines.push(format!("  Node-wide: {}", describe_quota(quota)));
+        }
+        if self.usage.is_empty() {
+            lines.push("  No usage today".dimmed().to_string());
+        }
+        for usage in &self.usage {
+            lines.push(format!(
+                "  {}  {} queries, {} bytes today",
+                short_peer_id(&usage.peer_id),
+                usage.queries,
+                usage.bytes
+            ));
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for referral command.
+#[derive(Debug, Serialize)]
+pub struct ReferralOutput {
+    pub hash: String,
+    pub title: String,
+    /// Price of the whole content.
+    pub price: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub referral: Option<ReferralPolicy>,
+}
+
+impl Render for ReferralOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![format!(
+            "{} {} ({}), whole content {}",
+            "Referral:".bold(),
+            self.title,
+            short_hash(&self.hash),
+            format_ndl(self.price)
+        )];
+        match &self.referral {
+            Some(referral) => lines.push(format!(
+                "  {}% to the referrer ({} per query)",
+                referral.basis_points as f64 / 100.0,
+                format_ndl(referral.amount(self.price))
+            )),
+            None => lines.push("  No referral cut".dimmed().to_string()),
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for share command.
+#[derive(Debug, Serialize)]
+pub struct ShareOutput {
+    pub hash: String,
+    pub title: String,
+    /// Peer the token is bound to; any holder may use it if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grantee: Option<String>,
+    pub expires_at: u64,
+    /// Encoded capability token.
+    pub capability: String,
+}
+
+impl Render for ShareOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![format!(
+            "{} {} ({})",
+            "Shared".green().bold(),
+            short_hash(&self.hash),
+            self.title
+        )];
+        lines.push(format!(
+            "  For:     {}",
+            self.grantee
+                .as_deref()
+                .unwrap_or("anyone holding the token")
+        ));
+        lines.push(format!("  Expires: {}", format_timestamp(self.expires_at)));
+        lines.push(String::new());
+        lines.push(self.capability.clone());
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for attribution, verify-attribution and endorse-attribution
+/// commands.
+#[derive(Debug, Serialize)]
+pub struct AttributionOutput {
+    pub operation: String,
+    pub hash: String,
+    pub title: String,
+    pub owner: String,
+    pub sources: Vec<AttributionSourceRow>,
+    pub attestations: usize,
+    /// Peers whose signatures verified, issuer first.
+    pub signers: Vec<String>,
+    /// File the certificate was written to, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub certificate: AttributionCertificate,
+}
+
+/// A root source in attribution output.
+#[derive(Debug, Serialize)]
+pub struct AttributionSourceRow {
+    pub hash: String,
+    pub owner: String,
+    pub weight: u32,
+}
+
+impl Render for AttributionOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![
+            format!(
+                "{} {} ({})",
+                format!("Attribution certificate {}:", self.operation)
+                    .green()
+                    .bold(),
+                short_hash(&self.hash),
+                self.title
+            ),
+            format!("{} {}", "Owner:".bold(), self.owner),
+            format!("{} {}", "Sources:".bold(), self.sources.len()),
+        ];
+        for source in &self.sources {
+            lines.push(format!(
+                "  {} {} (weight {})",
+                short_hash(&source.hash),
+                short_peer_id(&source.owner),
+                source.weight
+            ));
+        }
+        lines.push(format!("{} {}", "Attestations:".bold(), self.attestations));
+        lines.push(format!("{}", "Signed by:".bold()));
+        for signer in &self.signers {
+            lines.push(format!("  {}", signer));
+        }
+        if let Some(path) = &self.path {
+            lines.push(format!("{} {}", "Written to".bold(), path));
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for update command.
+#[derive(Debug, Serialize)]
+pub struct UpdateOutput {
+    pub previous_hash: String,
+    pub previous_version: u32,
+    pub new_hash: String,
+    pub new_version: u32,
+    pub version_root: String,
+}
+
+impl Render for UpdateOutput {
+    fn render_human(&self) -> String {
+        format!(
+            "{} {} (v{})\n{} {} (v{})\n{} {}",
+            "Previous:".bold(),
+            short_hash(&self.previous_hash),
+            self.previous_version,
+            "New:".green().bold(),
+            short_hash(&self.new_hash),
+            self.new_version,
+            "Version root:".bold(),
+            short_hash(&self.version_root)
+        )
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for synthesize command.
+#[derive(Debug, Serialize)]
+pub struct SynthesizeOutput {
+    pub hash: String,
+    pub source_count: usize,
+    pub provenance_roots: usize,
+    pub published: bool,
+    pub price: Option<u64>,
+}
+
+impl Render for SynthesizeOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![
+            format!("{} {}", "L3 hash:".green().bold(), self.hash),
+            format!("{} {}", "Sources:".bold(), self.source_count),
+            format!("{} {}", "Provenance roots:".bold(), self.provenance_roots),
+        ];
+
+        if self.published {
+            if let Some(price) = self.price {
+                lines.push(format!(
+                    "{} {} ({})",
+                    "Published:".bold(),
+                    "Yes".green(),
+                    format_ndl(price)
+                ));
+            } else {
+                lines.push(format!("{} {}", "Published:".bold(), "Yes".green()));
+            }
+        }
+
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for build-l2 command.
+#[derive(Debug, Serialize)]
+pub struct BuildL2Output {
+    pub hash: String,
+    pub entity_count: usize,
+    pub relationship_count: usize,
+    pub source_count: usize,
+}
+
+impl Render for BuildL2Output {
+    fn render_human(&self) -> String {
+        format!(
+            "{} {}\n{} {}\n{} {}\n{} {}",
+            "L2 Entity Graph:".green().bold(),
+            self.hash,
+            "Entities:".bold(),
+            self.entity_count,
+            "Relationships:".bold(),
+            self.relationship_count,
+            "Sources:".bold(),
+            self.source_count
+        )
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for merge-l2 command.
+#[derive(Debug, Serialize)]
+pub struct MergeL2Output {
+    pub hash: String,
+    pub merged_count: usize,
+    pub entity_count: usize,
+    pub relationship_count: usize,
+}
+
+impl Render for MergeL2Output {
+    fn render_human(&self) -> String {
+        format!(
+            "{} {}\n{} {}\n{} {}\n{} {}",
+            "Merged L2 Graph:".green().bold(),
+            self.hash,
+            "Graphs merged:".bold(),
+            self.merged_count,
+            "Total entities:".bold(),
+            self.entity_count,
+            "Total relationships:".bold(),
+            self.relationship_count
+        )
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for status command.
+#[derive(Debug, Default, Serialize)]
+pub struct StatusOutput {
+    pub running: bool,
+    pub peer_id: String,
+    pub uptime_secs: Option<u64>,
+    pub connected_peers: u32,
+    pub shared_content: u32,
+    pub private_content: u32,
+    pub pending_payments: u32,
+    pub pending_amount: u64,
+    pub open_channels: u32,
+    /// Our balance locked in open channels.
+    pub channel_balance: u64,
+    pub total_earned: u64,
+    /// Bytes held in the content store and the cache.
+    pub storage_bytes: u64,
+    /// Fraction of manifest lookups answered from memory.
+    pub cache_hit_rate: f64,
+    /// Component health reported by the running node.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health: Option<HealthReport>,
+    /// Incoming announcement filter counters of the running node.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub announcements: Option<AnnouncementStatsOutput>,
+}
+
+/// Incoming announcement filter counters, shown in node status.
+#[derive(Debug, Serialize)]
+pub struct AnnouncementStatsOutput {
+    pub accepted: u64,
+    pub dropped: u64,
+    pub dropped_rate_limited: u64,
+    pub dropped_invalid_signature: u64,
+    pub dropped_price: u64,
+    pub dropped_title: u64,
+    pub dropped_reputation: u64,
+    pub collapsed_duplicates: u64,
+}
+
+impl From<nodalync_ops::AnnouncementFilterStats> for AnnouncementStatsOutput {
+    fn from(stats: nodalync_ops::AnnouncementFilterStats) -> Self {
+        Self {
+            accepted: stats.accepted,
+            dropped: stats.dropped_total(),
+            dropped_rate_limited: stats.dropped_rate_limited,
+            dropped_invalid_signature: stats.dropped_invalid_signature,
+            dropped_price: stats.dropped_price,
+            dropped_title: stats.dropped_title,
+            dropped_reputation: stats.dropped_reputation,
+            collapsed_duplicates: stats.collapsed_duplicates,
+        }
+    }
+}
+
+impl Render for StatusOutput {
+    fn render_human(&self) -> String {
+        let status = if self.running {
+            "running".green()
+        } else {
+            "stopped".red()
+        };
+
+        let mut lines = vec![
+            format!("{} {}", "Node:".bold(), status),
+            format!("{} {}", "PeerId:".bold(), short_peer_id(&self.peer_id)),
+        ];
+
+        if let Some(uptime) = self.uptime_secs {
+            lines.push(format!("{} {}", "Uptime:".bold(), format_duration(uptime)));
+        }
+
+        lines.push(format!(
+            "{} {} connected",
+            "Peers:".bold(),
+            self.connected_peers
+        ));
+        lines.push(format!(
+            "{} {} shared, {} private",
+            "Content:".bold(),
+            self.shared_content,
+            self.private_content
+        ));
+        lines.push(format!(
+            "{} {} payments ({})",
+            "Pending:".bold(),
+            self.pending_payments,
+            format_ndl(self.pending_amount)
+        ));
+        lines.push(format!(
+            "{} {} open ({} locked)",
+            "Channels:".bold(),
+            self.open_channels,
+            format_ndl(self.channel_balance)
+        ));
+        lines.push(format!(
+            "{} {}",
+            "Earned:".bold(),
+            format_ndl(self.total_earned)
+        ));
+        lines.push(format!(
+            "{} {:.1} MB, manifest cache {:.0}% hits",
+            "Storage:".bold(),
+            self.storage_bytes as f64 / (1024.0 * 1024.0),
+            self.cache_hit_rate * 100.0
+        ));
+
+        if let Some(ref a) = self.announcements {
+            lines.push(format!(
+                "{} {} accepted, {} dropped, {} collapsed",
+                "Announcements:".bold(),
+                a.accepted,
+                a.dropped,
+                a.collapsed_duplicates
+            ));
+            if a.dropped > 0 {
+                lines.push(format!(
+                    "  rate limit {}, signature {}, price {}, title {}, reputation {}",
+                    a.dropped_rate_limited,
+                    a.dropped_invalid_signature,
+                    a.dropped_price,
+                    a.dropped_title,
+                    a.dropped_reputation
+                ));
+            }
+        }
+
+        if let Some(ref health) = self.health {
+            lines.push(format!(
+                "{} {}",
+                "Health:".bold(),
+                colorize_health(health.status)
+            ));
+            for (component, ch) in &health.components {
+                let mut line = format!(
+                    "  {:<12} {}",
+                    component.to_string(),
+                    colorize_health(ch.state)
+                );
+                if let Some(ref detail) = ch.detail {
+                    line.push_str(&format!(" ({})", detail));
+                }
+                lines.push(line);
+            }
+            if let Some(ref err) = health.last_error {
+                lines.push(format!(
+                    "{} [{}] {}",
+                    "Last error:".bold(),
+                    err.component,
+                    err.message
+                ));
+            }
+        }
+
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Color a health state for human output.
+fn colorize_health(state: HealthState) -> colored::ColoredString {
+    match state {
+        HealthState::Ok => state.to_string().green(),
+        HealthState::Disabled => state.to_string().dimmed(),
+        HealthState::Degraded => state.to_string().yellow(),
+        HealthState::Down => state.to_string().red(),
+    }
+}
+
+/// Output for start command.
+#[derive(Debug, Serialize)]
+pub struct StartOutput {
+    pub peer_id: String,
+    pub listen_addresses: Vec<String>,
+    pub connected_peers: u32,
+    pub daemon: bool,
+}
+
+impl Render for StartOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![
+            format!("{}", "Nodalync node started!".green().bold()),
+            format!("{} {}", "PeerId:".bold(), short_peer_id(&self.peer_id)),
+        ];
+
+        for addr in &self.listen_addresses {
+            lines.push(format!("{} {}", "Listening on:".bold(), addr));
+        }
+
+        lines.push(format!(
+            "{} {} peers",
+            "Connected to:".bold(),
+            self.connected_peers
+        ));
+
+        if self.daemon {
+            lines.push(format!("{}", "Running in background.".dimmed()));
+        }
+
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for stop command.
+#[derive(Debug, Serialize)]
+pub struct StopOutput {
+    pub success: bool,
+}
+
+impl Render for StopOutput {
+    fn render_human(&self) -> String {
+        if self.success {
+            format!(
+                "{}\n{}",
+                "Shutting down gracefully...".yellow(),
+                "Node stopped.".green()
+            )
+        } else {
+            "Failed to stop node.".red().to_string()
+        }
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for logs command.
+#[derive(Debug, Serialize)]
+pub struct LogsOutput {
+    pub entries: Vec<LogEntry>,
+}
+
+impl Render for LogsOutput {
+    fn render_human(&self) -> String {
+        if self.entries.is_empty() {
+            return "No log entries.".dimmed().to_string();
+        }
+        self.entries
+            .iter()
+            .map(render_log_entry)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for the replay command.
+#[derive(Debug, Serialize)]
+pub struct ReplayOutput {
+    pub log: String,
+    pub entries: Vec<ReplayEntryOutput>,
+    /// Inbound requests answered by the handlers.
+    pub responded: usize,
+    /// Inbound requests and broadcasts replayed in all.
+    pub replayed: usize,
+    /// Entries that didn't decode as wire messages.
+    pub undecodable: usize,
+    /// Entries whose handler returned an error.
+    pub failed: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplayEntryOutput {
+    pub seq: u64,
+    pub timestamp: u64,
+    pub direction: String,
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_type: Option<String>,
+    /// The peer, or the topic for broadcasts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decode_error: Option<String>,
+    /// What replaying it did (responded, no_response, handled, skipped,
+    /// failed).
+    pub outcome: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Render for ReplayOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![format!("{} {}", "Replay of".bold(), self.log)];
+        for e in &self.entries {
+            let message = e
+                .message_type
+                .clone()
+                .unwrap_or_else(|| "undecodable".to_string());
+            let mut line = format!(
+                "  {:>6} {} {:<8} {:<8} {:<24}",
+                e.seq,
+                logging::format_timestamp(e.timestamp).dimmed(),
+                e.direction,
+                e.kind,
+                message
+            );
+            if let Some(remote) = &e.remote {
+                line.push_str(&format!(" {}", remote.dimmed()));
+            }
+            let outcome = match (&e.response, &e.error) {
+                (Some(response), _) => format!("-> {}", response).green().to_string(),
+                (_, Some(error)) => format!("failed: {}", error).red().to_string(),
+                _ if e.outcome == "skipped" => String::new(),
+                _ => e.outcome.replace('_', " ").yellow().to_string(),
+            };
+            if !outcome.is_empty() {
+                line.push_str(&format!("  {}", outcome));
+            }
+            if let Some(decode_error) = &e.decode_error {
+                line.push_str(&format!("  ({})", decode_error).dimmed().to_string());
+            }
+            lines.push(line);
+        }
+        lines.push(format!(
+            "{} entries, {} replayed, {} answered, {} undecodable, {} failed",
+            self.entries.len(),
+            self.replayed,
+            self.responded,
+            self.undecodable,
+            self.failed
+        ));
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for the peers list command.
+#[derive(Debug, Serialize)]
+pub struct PeerListOutput {
+    pub total: usize,
+    pub peers: Vec<PeerLatencyInfo>,
+}
+
+/// A known peer and its measured latency.
+#[derive(Debug, Serialize)]
+pub struct PeerLatencyInfo {
+    pub peer_id: String,
+    pub reputation: i64,
+    pub last_seen: u64,
+    pub probes: u64,
+    pub last_probe: Option<u64>,
+    pub p50_ms: Option<u32>,
+    pub p90_ms: Option<u32>,
+    pub p99_ms: Option<u32>,
+    pub jitter_ms: Option<f64>,
+    pub loss_rate: Option<f64>,
+    pub qos_score: Option<f64>,
+}
+
+impl Render for PeerListOutput {
+    fn render_human(&self) -> String {
+        if self.peers.is_empty() {
+            return "No known peers.".dimmed().to_string();
+        }
+
+        let mut lines = vec![format!(
+            "{}",
+            format!(
+                "{} known {}",
+                self.total,
+                if self.total == 1 { "peer" } else { "peers" }
+            )
+            .bold()
+        )];
+        for peer in &self.peers {
+            let ms = |value: Option<u32>| value.map_or("-".to_string(), |v| format!("{}ms", v));
+            let latency = match peer.qos_score {
+                None => "not probed".dimmed().to_string(),
+                Some(score) => format!(
+                    "p50 {}  p90 {}  p99 {}  jitter {}  loss {:.1}%  QoS {:.2}",
+                    ms(peer.p50_ms),
+                    ms(peer.p90_ms),
+                    ms(peer.p99_ms),
+                    peer.jitter_ms
+                        .map_or("-".to_string(), |j| format!("{:.0}ms", j)),
+                    peer.loss_rate.unwrap_or(0.0) * 100.0,
+                    score
+                ),
+            };
+            lines.push(format!(
+                "  {}  rep {:>4}  {}",
+                short_peer_id(&peer.peer_id),
+                peer.reputation,
+                latency
+            ));
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for the availability command.
+#[derive(Debug, Serialize)]
+pub struct AvailabilityOutput {
+    pub total: usize,
+    pub reachable: usize,
+    pub unreachable: usize,
+    pub content: Vec<AvailabilityInfo>,
+}
+
+/// The latest availability check of a content hash.
+#[derive(Debug, Serialize)]
+pub struct AvailabilityInfo {
+    pub hash: String,
+    pub title: Option<String>,
+    pub reachable: bool,
+    pub checker: String,
+    pub dialed: Option<String>,
+    pub latency_ms: Option<u32>,
+    pub error: Option<String>,
+    pub checked_at: u64,
+    pub checks: u64,
+    pub failures: u64,
+    pub consecutive_failures: u32,
+}
+
+impl Render for AvailabilityOutput {
+    fn render_human(&self) -> String {
+        if self.content.is_empty() {
+            return "No availability checks recorded.".dimmed().to_string();
+        }
+
+        let mut lines = vec![format!(
+            "{}",
+            format!(
+                "{} checked: {} reachable, {} unreachable",
+                self.total, self.reachable, self.unreachable
+            )
+            .bold()
+        )];
+        for item in &self.content {
+            let status = if item.reachable {
+                format!(
+                    "{} via {} ({})",
+                    "reachable".green(),
+                    item.dialed.as_deref().unwrap_or("-"),
+                    item.latency_ms
+                        .map_or("-".to_string(), |ms| format!("{}ms", ms))
+                )
+            } else {
+                format!(
+                    "{} ({} in a row): {}",
+                    "unreachable".red(),
+                    item.consecutive_failures,
+                    item.error.as_deref().unwrap_or("not reachable")
+                )
+            };
+            lines.push(format!(
+                "  {}  {}  {}",
+                short_hash(&item.hash),
+                item.title.as_deref().unwrap_or("-"),
+                status
+            ));
+            lines.push(format!(
+                "      {} failed of {} checks, last {} by {}",
+                item.failures,
+                item.checks,
+                format_timestamp(item.checked_at),
+                short_peer_id(&item.checker)
+            ));
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for the net inspect command.
+#[derive(Debug, Serialize)]
+pub struct NetInspectOutput {
+    pub file: String,
+    pub sessions: Vec<CaptureSessionOutput>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CaptureSessionOutput {
+    pub started_at: u64,
+    pub ended_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local_peer: Option<String>,
+    pub inbound: u64,
+    pub outbound: u64,
+    /// Messages by type, most frequent first.
+    pub message_types: Vec<MessageTypeCountOutput>,
+    pub undecodable: u64,
+    /// Query errors by error code name.
+    pub error_codes: Vec<ErrorCodeCountOutput>,
+    /// Undecodable messages and query errors.
+    pub errors: u64,
+    /// Share of messages that were errors, in [0, 1].
+    pub error_rate: f64,
+    pub paid: PaymentFlowOutput,
+    pub received: PaymentFlowOutput,
+    /// Traffic per remote peer, busiest first.
+    pub peers: Vec<CapturePeerOutput>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MessageTypeCountOutput {
+    pub message_type: String,
+    pub inbound: u64,
+    pub outbound: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorCodeCountOutput {
+    pub code: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PaymentFlowOutput {
+    pub payments: u64,
+    /// Total amount (in tinybars).
+    pub amount: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CapturePeerOutput {
+    pub peer: String,
+    pub inbound: u64,
+    pub outbound: u64,
+    pub errors: u64,
+    pub paid: PaymentFlowOutput,
+    pub received: PaymentFlowOutput,
+}
+
+impl Render for NetInspectOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![format!("{} {}", "Capture".bold(), self.file)];
+        for (i, s) in self.sessions.iter().enumerate() {
+            let mut header = format!(
+                "{} {}  {} - {} ({})",
+                "Session".bold(),
+                i + 1,
+                logging::format_timestamp(s.started_at),
+                logging::format_timestamp(s.ended_at),
+                format_duration((s.ended_at - s.started_at) / 1000)
+            );
+            if let Some(peer) = &s.local_peer {
+                header.push_str(&format!("  {}", peer.dimmed()));
+            }
+            lines.push(String::new());
+            lines.push(header);
+
+            let errors = format!("{} errors ({:.1}%)", s.errors, s.error_rate * 100.0);
+            lines.push(format!(
+                "  Messages: {} ({} in, {} out), {}",
+                s.inbound + s.outbound,
+                s.inbound,
+                s.outbound,
+                if s.errors > 0 {
+                    errors.red().to_string()
+                } else {
+                    errors
+                }
+            ));
+            for t in &s.message_types {
+                lines.push(format!(
+                    "    {:<24} {:>6} in {:>6} out",
+                    t.message_type, t.inbound, t.outbound
+                ));
+            }
+            if !s.error_codes.is_empty() {
+                let codes: Vec<String> = s
+                    .error_codes
+                    .iter()
+                    .map(|c| format!("{} {}", c.code, c.count))
+                    .collect();
+                lines.push(format!("  Query errors: {}", codes.join(", ")));
+            }
+            lines.push(format!(
+                "  Payments: paid {} ({}), received {} ({})",
+                s.paid.payments,
+                format_ndl(s.paid.amount),
+                s.received.payments,
+                format_ndl(s.received.amount)
+            ));
+            if !s.peers.is_empty() {
+                lines.push("  Peers:".to_string());
+            }
+            for p in &s.peers {
+                lines.push(format!(
+                    "    {}  {} in, {} out, {} errors, paid {}, received {}",
+                    p.peer,
+                    p.inbound,
+                    p.outbound,
+                    p.errors,
+                    format_ndl(p.paid.amount),
+                    format_ndl(p.received.amount)
+                ));
+            }
+        }
+        if self.sessions.is_empty() {
+            lines.push("No sessions captured.".dimmed().to_string());
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Render one log entry as a human-readable line.
+pub fn render_log_entry(entry: &LogEntry) -> String {
+    let padded = format!("{:>5}", entry.level);
+    let level = match entry.level.as_str() {
+        "ERROR" => padded.red().bold(),
+        "WARN" => padded.yellow(),
+        "INFO" => padded.green(),
+        _ => padded.dimmed(),
+    };
+    let mut line = format!(
+        "{} {} {}: {}",
+        logging::format_timestamp(entry.ts).dimmed(),
+        level,
+        entry.target.dimmed(),
+        entry.message
+    );
+    for (key, value) in &entry.fields {
+        match value {
+            serde_json::Value::String(s) => line.push_str(&format!(" {}={}", key, s)),
+            other => line.push_str(&format!(" {}={}", key, other)),
+        }
+    }
+    line
+}
+
+/// Summary of a content scrub, persisted between runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScrubSummary {
+    pub started_at: u64,
+    pub finished_at: u64,
+    pub checked: u32,
+    pub issues: Vec<ScrubIssueSummary>,
+}
+
+/// A blob that failed its integrity check.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScrubIssueSummary {
+    pub hash: String,
+    pub title: String,
+    /// "restored_from_cache", "refetched" or "quarantined".
+    pub outcome: String,
+}
+
+/// Output for doctor command.
+#[derive(Debug, Serialize)]
+pub struct DoctorOutput {
+    pub last_scrub: Option<ScrubSummary>,
+    /// Hours between scheduled scrubs (0 when disabled).
+    pub scrub_interval_hours: u64,
+    pub quarantined: Vec<String>,
+}
+
+impl Render for DoctorOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![format!("{}", "Content integrity".bold())];
+
+        match self.last_scrub {
+            Some(ref scrub) => {
+                let status = if scrub.issues.is_empty() {
+                    "ok".green()
+                } else {
+                    format!("{} issue(s)", scrub.issues.len()).yellow()
+                };
+                lines.push(format!(
+                    "  {} {} UTC, {} blobs checked, {}",
+                    "Last scrub:".bold(),
+                    logging::format_timestamp(scrub.finished_at),
+                    scrub.checked,
+                    status
+                ));
+                for issue in &scrub.issues {
+                    let outcome = match issue.outcome.as_str() {
+                        "quarantined" => issue.outcome.red(),
+                        _ => issue.outcome.replace('_', " ").green(),
+                    };
+                    lines.push(format!(
+                        "    {} {} [{}]",
+                        &issue.hash[..issue.hash.len().min(16)],
+                        issue.title,
+                        outcome
+                    ));
+                }
+            }
+            None => lines.push(format!("  {} never", "Last scrub:".bold())),
+        }
+
+        if self.scrub_interval_hours == 0 {
+            lines.push(format!("  {} disabled", "Schedule:".bold()));
+        } else {
+            lines.push(format!(
+                "  {} every {}h",
+                "Schedule:".bold(),
+                self.scrub_interval_hours
+            ));
+        }
+
+        if self.quarantined.is_empty() {
+            lines.push(format!("  {} none", "Quarantined:".bold()));
+        } else {
+            lines.push(format!(
+                "  {} {}",
+                "Quarantined:".bold(),
+                self.quarantined.len().to_string().red()
+            ));
+            for hash in &self.quarantined {
+                lines.push(format!("    {}", hash));
+            }
+        }
+
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for delete command.
+#[derive(Debug, Serialize)]
+pub struct DeleteOutput {
+    pub hash: String,
+}
+
+impl Render for DeleteOutput {
+    fn render_human(&self) -> String {
+        format!(
+            "{} {} (withdrawn from the network, provenance preserved)",
+            "Deleted:".green().bold(),
+            short_hash(&self.hash)
+        )
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+/// Shorten a hash for display (first 8 + last 4 characters).
+pub fn short_hash(hash: &str) -> String {
+    if hash.len() <= 16 {
+        hash.to_string()
+    } else {
+        format!("{}...{}", &hash[..8], &hash[hash.len() - 4..])
+    }
+}
+
+/// Shorten a peer ID for display.
+pub fn short_peer_id(peer_id: &str) -> String {
+    if peer_id.len() <= 20 {
+        peer_id.to_string()
+    } else {
+        format!("{}...", &peer_id[..16])
+    }
+}
+
+/// Format a timestamp as a human-readable date.
+fn format_timestamp(ts: u64) -> String {
+    // Simple ISO-like format: YYYY-MM-DD
+    // This is a simplified version; in production you'd use chrono
+    let secs = ts / 1000;
+    let days = secs / 86400;
+    let years_since_1970 = days / 365;
+    let year = 1970 + years_since_1970;
+    let day_of_year = days % 365;
+    let month = (day_of_year / 30) + 1;
+    let day = (day_of_year % 30) + 1;
+    format!("{:04}-{:02}-{:02}", year, month.min(12), day.min(31))
+}
+
+/// Format duration in seconds as human-readable.
+fn format_duration(secs: u64) -> String {
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m {}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h {}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
+/// Convert manifest to summary.
+pub fn manifest_to_summary(m: &Manifest) -> ManifestSummary {
+    ManifestSummary {
+        hash: m.hash.to_string(),
+        title: m.metadata.title.clone(),
+        version: m.version.number,
+        visibility: format!("{:?}", m.visibility),
+        price: m.economics.price,
+        queries: m.economics.total_queries,
+        content_type: format!("{:?}", m.content_type),
+    }
+}
+
+/// Convert L1Summary to preview mentions.
+pub fn l1_to_preview(summary: &L1Summary) -> PreviewMentions {
+    PreviewMentions {
+        total: summary.mention_count as usize,
+        preview: summary
+            .preview_mentions
+            .iter()
+            .map(|m| m.content.clone())
+            .collect(),
+    }
+}
+
+/// Convert a tracked invoice to a summary.
+pub fn invoice_to_summary(record: &InvoiceRecord) -> InvoiceSummary {
+    let invoice = &record.invoice;
+    InvoiceSummary {
+        hash: invoice.hash.to_string(),
+        payee: nodalync_crypto::peer_id_to_string(&invoice.payee),
+        amount: invoice.amount,
+        memo: invoice.memo.clone(),
+        reference: invoice.reference.map(|r| r.to_string()),
+        created_at: invoice.created_at,
+        expires_at: invoice.expires_at,
+        direction: record.direction.to_string(),
+        status: record.status.to_string(),
+        payer: record
+            .payer
+            .as_ref()
+            .map(nodalync_crypto::peer_id_to_string),
+        paid_at: record.paid_at,
+    }
+}
+
+/// Convert a stored group to a summary.
+pub fn group_to_summary(stored: &StoredGroup, me: &nodalync_crypto::PeerId) -> GroupSummary {
+    let group = &stored.group;
+    GroupSummary {
+        id: group.id.to_string(),
+        name: group.name.clone(),
+        owner: nodalync_crypto::peer_id_to_string(&group.owner),
+        owned: group.owner == *me,
+        version: group.version,
+        members: group
+            .members
+            .iter()
+            .map(nodalync_crypto::peer_id_to_string)
+            .collect(),
+        updated_at: group.updated_at,
+        fetched_at: stored.fetched_at,
+    }
+}
+
+/// Output for earnings command.
+#[derive(Debug, Serialize)]
+pub struct EarningsOutput {
+    pub content: Vec<ContentEarning>,
+    pub total_earned: u64,
+    pub total_queries: u64,
+}
+
+/// Individual content earning record.
+#[derive(Debug, Serialize)]
+pub struct ContentEarning {
+    pub hash: String,
+    pub title: String,
+    pub queries: u64,
+    pub total_earned: u64,
+    pub price: u64,
+}
+
+impl Render for EarningsOutput {
+    fn render_human(&self) -> String {
+        if self.content.is_empty() {
+            return "No earnings yet.".dimmed().to_string();
+        }
+
+        let mut lines = vec![format!(
+            "{} {} from {} queries\n",
+            "Total Earnings:".green().bold(),
+            format_ndl(self.total_earned),
+            self.total_queries
+        )];
+
+        lines.push(format!("{}", "Content breakdown:".bold()));
+        for earning in &self.content {
+            let hash_short = short_hash(&earning.hash);
+            lines.push(format!(
+                "  {} \"{}\" - {} ({} queries @ {})",
+                hash_short.cyan(),
+                truncate_title(&earning.title, 30),
+                format_ndl(earning.total_earned).green(),
+                earning.queries,
+                format_ndl(earning.price)
+            ));
+        }
+
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for stats command.
+#[derive(Debug, Serialize)]
+pub struct ContentStatsOutput {
+    pub hash: String,
+    pub title: String,
+    pub previews: u64,
+    pub queries: u64,
+    /// Queries served without payment (free content).
+    pub free_queries: u64,
+    pub unique_consumers: u64,
+    pub unique_payers: u64,
+    pub revenue: u64,
+    pub first_access: Option<u64>,
+    pub last_access: Option<u64>,
+    /// Usage reports sent back by consumers.
+    pub usage_reports: u64,
+    pub bytes_read: u64,
+    pub average_bytes_read: Option<u64>,
+    pub average_rating: Option<f64>,
+    pub rated_reports: u64,
+    pub average_context_tokens: Option<u64>,
+}
+
+impl Render for ContentStatsOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![
+            format!("{} \"{}\"", short_hash(&self.hash).cyan(), self.title),
+            format!(
+                "  {} {} previews, {} queries ({} free), {} consumers ({} paying)",
+                "Access:".bold(),
+                self.previews,
+                self.queries,
+                self.free_queries,
+                self.unique_consumers,
+                self.unique_payers
+            ),
+            format!(
+                "  {} {}",
+                "Revenue:".bold(),
+                format_ndl(self.revenue).green()
+            ),
+        ];
+        if let (Some(first), Some(last)) = (self.first_access, self.last_access) {
+            lines.push(format!(
+                "  {} {} to {}",
+                "Active:".bold(),
+                format_timestamp(first),
+                format_timestamp(last)
+            ));
+        }
+
+        if self.usage_reports == 0 {
+            lines.push(format!("  {}", "No usage reports.".dimmed()));
+            return lines.join("\n");
+        }
+        lines.push(format!(
+            "  {} {} reports, {} bytes read (avg {})",
+            "Usage:".bold(),
+            self.usage_reports,
+            self.bytes_read,
+            self.average_bytes_read.unwrap_or(0)
+        ));
+        if let Some(rating) = self.average_rating {
+            lines.push(format!(
+                "  {} {:.1}/5 from {} reports",
+                "Rating:".bold(),
+                rating,
+                self.rated_reports
+            ));
+        }
+        if let Some(tokens) = self.average_context_tokens {
+            lines.push(format!("  {} {} tokens (avg)", "Context:".bold(), tokens));
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for report-usage command.
+#[derive(Debug, Serialize)]
+pub struct UsageReportOutput {
+    pub hash: String,
+    pub bytes_read: u64,
+    pub context_tokens: Option<u32>,
+    pub rating: Option<u8>,
+}
+
+impl Render for UsageReportOutput {
     fn render_human(&self) -> String {
         format!(
-            "{} {}\n{} {}\n{} {}\n{} {}",
-            "Merged L2 Graph:".green().bold(),
-            self.hash,
-            "Graphs merged:".bold(),
-            self.merged_count,
-            "Total entities:".bold(),
-            self.entity_count,
-            "Total relationships:".bold(),
-            self.relationship_count
+            "{} {} ({} bytes read)",
+            "Usage reported for".green(),
+            short_hash(&self.hash),
+            self.bytes_read
         )
     }
 
@@ -638,54 +3179,549 @@ impl Render for MergeL2Output {
     }
 }
 
-/// Output for status command.
+/// Output for ledger command.
 #[derive(Debug, Serialize)]
-pub struct StatusOutput {
-    pub running: bool,
-    pub peer_id: String,
-    pub uptime_secs: Option<u64>,
-    pub connected_peers: u32,
-    pub shared_content: u32,
-    pub private_content: u32,
-    pub pending_payments: u32,
-    pub pending_amount: u64,
+pub struct LedgerOutput {
+    /// Account the entries are filtered to, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    /// Most recent entries, oldest first.
+    pub entries: Vec<LedgerEntryRow>,
+    pub balances: Vec<LedgerBalanceRow>,
+    pub total_debits: u64,
+    pub total_credits: u64,
+    pub checks: Vec<ReconciliationRow>,
+    pub reconciled: bool,
 }
 
-impl Render for StatusOutput {
+#[derive(Debug, Serialize)]
+pub struct LedgerEntryRow {
+    pub transaction_id: u64,
+    pub timestamp: u64,
+    pub event: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    pub account: String,
+    pub debit: u64,
+    pub credit: u64,
+    pub balance: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LedgerBalanceRow {
+    pub account: String,
+    pub balance: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReconciliationRow {
+    pub account: String,
+    pub ledger_balance: i64,
+    pub actual_balance: i64,
+    pub difference: i64,
+}
+
+/// Format a signed tinybar amount.
+fn format_signed_ndl(units: i64) -> String {
+    if units < 0 {
+        format!("-{}", format_ndl(units.unsigned_abs()))
+    } else {
+        format_ndl(units as u64)
+    }
+}
+
+impl Render for LedgerOutput {
     fn render_human(&self) -> String {
-        let status = if self.running {
-            "running".green()
+        let mut lines = Vec::new();
+
+        if self.entries.is_empty() {
+            lines.push("No ledger entries yet.".dimmed().to_string());
         } else {
-            "stopped".red()
-        };
+            lines.push(format!("{}", "Entries:".bold()));
+            for entry in &self.entries {
+                let amount = if entry.debit > 0 {
+                    format!("Dr {}", format_ndl(entry.debit))
+                } else {
+                    format!("Cr {}", format_ndl(entry.credit))
+                };
+                lines.push(format!(
+                    "  #{:<5} {} {:<20} {:<12} {:>22}  bal {}",
+                    entry.transaction_id,
+                    format_timestamp(entry.timestamp),
+                    entry.event,
+                    entry.account.cyan(),
+                    amount,
+                    format_signed_ndl(entry.balance)
+                ));
+            }
+        }
+
+        lines.push(format!("\n{}", "Balances:".bold()));
+        for row in &self.balances {
+            lines.push(format!(
+                "  {:<12} {}",
+                row.account.cyan(),
+                format_signed_ndl(row.balance)
+            ));
+        }
+        lines.push(format!(
+            "  Debits {} / Credits {}",
+            format_ndl(self.total_debits),
+            format_ndl(self.total_credits)
+        ));
+
+        lines.push(format!("\n{}", "Reconciliation:".bold()));
+        for check in &self.checks {
+            let status = if check.difference == 0 {
+                "ok".green().to_string()
+            } else {
+                format!("off by {}", format_signed_ndl(check.difference))
+                    .red()
+                    .to_string()
+            };
+            lines.push(format!(
+                "  {:<12} ledger {} / actual {} ({})",
+                check.account.cyan(),
+                format_signed_ndl(check.ledger_balance),
+                format_signed_ndl(check.actual_balance),
+                status
+            ));
+        }
+        if self.total_debits != self.total_credits {
+            lines.push(format!("  {}", "Debits and credits do not balance".red()));
+        }
+
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for ledger CSV export.
+#[derive(Debug, Serialize)]
+pub struct LedgerExportOutput {
+    pub path: String,
+    pub entries: usize,
+}
+
+impl Render for LedgerExportOutput {
+    fn render_human(&self) -> String {
+        format!(
+            "{} {} ledger entries to {}",
+            "Exported".green(),
+            self.entries,
+            self.path
+        )
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Summary of an issued or received invoice.
+#[derive(Debug, Serialize)]
+pub struct InvoiceSummary {
+    pub hash: String,
+    pub payee: String,
+    pub amount: u64,
+    pub memo: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub direction: String,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paid_at: Option<u64>,
+}
+
+/// Output for invoice operations (create/send/fetch/pay).
+#[derive(Debug, Serialize)]
+pub struct InvoiceOutput {
+    pub operation: String,
+    #[serde(flatten)]
+    pub invoice: InvoiceSummary,
+}
+
+impl Render for InvoiceOutput {
+    fn render_human(&self) -> String {
+        let invoice = &self.invoice;
+        let mut output = format!(
+            "{} {}\n{} {}\n{} {}\n{} {}\n{} {}",
+            format!("Invoice {}:", self.operation).green().bold(),
+            invoice.hash,
+            "Payee:".bold(),
+            short_peer_id(&invoice.payee),
+            "Amount:".bold(),
+            format_ndl(invoice.amount),
+            "Status:".bold(),
+            invoice.status,
+            "Expires:".bold(),
+            format_timestamp(invoice.expires_at)
+        );
+        if !invoice.memo.is_empty() {
+            output.push_str(&format!("\n{} {}", "Memo:".bold(), invoice.memo));
+        }
+        if let Some(ref reference) = invoice.reference {
+            output.push_str(&format!(
+                "\n{} {}",
+                "Reference:".bold(),
+                short_hash(reference)
+            ));
+        }
+        output
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Summary of a group we own or have cached.
+#[derive(Debug, Serialize)]
+pub struct GroupSummary {
+    pub id: String,
+    pub name: String,
+    pub owner: String,
+    pub owned: bool,
+    pub version: u64,
+    pub members: Vec<String>,
+    pub updated_at: u64,
+    pub fetched_at: u64,
+}
+
+/// Output for group operations (create/update/fetch).
+#[derive(Debug, Serialize)]
+pub struct GroupOutput {
+    pub operation: String,
+    #[serde(flatten)]
+    pub group: GroupSummary,
+}
 
+impl Render for GroupOutput {
+    fn render_human(&self) -> String {
+        let group = &self.group;
         let mut lines = vec![
-            format!("{} {}", "Node:".bold(), status),
-            format!("{} {}", "PeerId:".bold(), short_peer_id(&self.peer_id)),
+            format!(
+                "{} {}",
+                format!("Group {}:", self.operation).green().bold(),
+                group.id
+            ),
+            format!("{} {}", "Name:".bold(), group.name),
+            format!("{} {}", "Owner:".bold(), short_peer_id(&group.owner)),
+            format!("{} {}", "Version:".bold(), group.version),
+            format!("{} {}", "Members:".bold(), group.members.len()),
         ];
+        for member in &group.members {
+            lines.push(format!("  {}", member));
+        }
+        lines.join("\n")
+    }
 
-        if let Some(uptime) = self.uptime_secs {
-            lines.push(format!("{} {}", "Uptime:".bold(), format_duration(uptime)));
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for group list command.
+#[derive(Debug, Serialize)]
+pub struct GroupListOutput {
+    pub groups: Vec<GroupSummary>,
+    pub total: usize,
+}
+
+impl Render for GroupListOutput {
+    fn render_human(&self) -> String {
+        if self.groups.is_empty() {
+            return "No groups.".dimmed().to_string();
         }
 
+        let mut lines = vec![format!("{} ({})", "Groups:".bold(), self.total)];
+        for group in &self.groups {
+            let source = if group.owned {
+                "owned".green().to_string()
+            } else {
+                "cached".dimmed().to_string()
+            };
+            lines.push(format!(
+                "  {} {:<6} v{:<4} {:>6} members  {}",
+                short_hash(&group.id),
+                source,
+                group.version,
+                group.members.len(),
+                group.name
+            ));
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for sync export and push.
+#[derive(Debug, Serialize)]
+pub struct SyncExportOutput {
+    pub operation: String,
+    pub peer_id: String,
+    /// File written, or peer the bundle was left with.
+    pub destination: String,
+    pub created_at: u64,
+    pub bytes: usize,
+}
+
+impl Render for SyncExportOutput {
+    fn render_human(&self) -> String {
+        [
+            format!(
+                "{}",
+                format!("Sync bundle {}", self.operation).green().bold()
+            ),
+            format!("{} {}", "Identity:".bold(), self.peer_id),
+            format!("{} {}", "To:".bold(), self.destination),
+            format!(
+                "{} {}",
+                "Created:".bold(),
+                format_timestamp(self.created_at)
+            ),
+            format!("{} {} bytes (encrypted)", "Size:".bold(), self.bytes),
+        ]
+        .join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for sync import and pull.
+#[derive(Debug, Serialize)]
+pub struct SyncImportOutput {
+    pub operation: String,
+    pub peer_id: String,
+    /// File read, or peer the bundle was pulled from.
+    pub source: String,
+    /// Whether the identity key was installed from the bundle.
+    pub identity_installed: bool,
+    pub created_at: u64,
+    pub channels_added: usize,
+    pub channels_updated: usize,
+    pub channels_unchanged: usize,
+    pub conflicts: Vec<SyncConflictInfo>,
+    pub receipts_added: usize,
+    pub accounts_registered: usize,
+}
+
+/// A channel both devices advanced.
+#[derive(Debug, Serialize)]
+pub struct SyncConflictInfo {
+    pub peer_id: String,
+    pub local_nonce: u64,
+    pub bundle_nonce: u64,
+}
+
+impl Render for SyncImportOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![
+            format!(
+                "{}",
+                format!("Sync bundle {}", self.operation).green().bold()
+            ),
+            format!("{} {}", "Identity:".bold(), self.peer_id),
+            format!("{} {}", "From:".bold(), self.source),
+            format!(
+                "{} {}",
+                "Exported:".bold(),
+                format_timestamp(self.created_at)
+            ),
+        ];
+        if self.identity_installed {
+            lines.push("Identity key installed on this device.".to_string());
+        }
         lines.push(format!(
-            "{} {} connected",
-            "Peers:".bold(),
-            self.connected_peers
-        ));
-        lines.push(format!(
-            "{} {} shared, {} private",
-            "Content:".bold(),
-            self.shared_content,
-            self.private_content
+            "{} {} added, {} updated, {} unchanged",
+            "Channels:".bold(),
+            self.channels_added,
+            self.channels_updated,
+            self.channels_unchanged
         ));
         lines.push(format!(
-            "{} {} payments ({})",
-            "Pending:".bold(),
-            self.pending_payments,
-            format_ndl(self.pending_amount)
+            "{} {} added",
+            "Receipts:".bold(),
+            self.receipts_added
         ));
+        if self.accounts_registered > 0 {
+            lines.push(format!(
+                "{} {} registered",
+                "Accounts:".bold(),
+                self.accounts_registered
+            ));
+        }
+        if !self.conflicts.is_empty() {
+            lines.push(
+                format!(
+                    "{} channels advanced on both devices (left unchanged):",
+                    self.conflicts.len()
+                )
+                .yellow()
+                .bold()
+                .to_string(),
+            );
+            for conflict in &self.conflicts {
+                lines.push(format!(
+                    "  {} nonce {} here, {} in bundle",
+                    short_peer_id(&conflict.peer_id),
+                    conflict.local_nonce,
+                    conflict.bundle_nonce
+                ));
+            }
+            lines.push(
+                "Resync these channels with their peers before paying over them."
+                    .dimmed()
+                    .to_string(),
+            );
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for replica export.
+#[derive(Debug, Serialize)]
+pub struct ReplicaExportOutput {
+    pub replica: String,
+    pub file: String,
+    pub items: usize,
+    pub expires_at: u64,
+    pub settlement_account: Option<String>,
+}
+
+impl Render for ReplicaExportOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![
+            format!("{}", "Replica catalog exported".green().bold()),
+            format!("{} {}", "Replica:".bold(), self.replica),
+            format!("{} {}", "To:".bold(), self.file),
+            format!("{} {}", "Items:".bold(), self.items),
+            format!(
+                "{} {}",
+                "Expires:".bold(),
+                format_timestamp(self.expires_at)
+            ),
+        ];
+        if let Some(account) = &self.settlement_account {
+            lines.push(format!("{} {}", "Settles to:".bold(), account));
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for replica import.
+#[derive(Debug, Serialize)]
+pub struct ReplicaImportOutput {
+    pub primary: String,
+    pub file: String,
+    pub expires_at: u64,
+    pub manifests_added: usize,
+    pub manifests_updated: usize,
+    pub manifests_withdrawn: usize,
+    pub account_registered: bool,
+}
+
+impl Render for ReplicaImportOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![
+            format!("{}", "Replica catalog imported".green().bold()),
+            format!("{} {}", "Primary:".bold(), self.primary),
+            format!("{} {}", "From:".bold(), self.file),
+            format!(
+                "{} {}",
+                "Expires:".bold(),
+                format_timestamp(self.expires_at)
+            ),
+            format!(
+                "{} {} added, {} updated, {} withdrawn",
+                "Content:".bold(),
+                self.manifests_added,
+                self.manifests_updated,
+                self.manifests_withdrawn
+            ),
+        ];
+        if self.account_registered {
+            lines.push("Primary's settlement account registered.".to_string());
+        }
+        lines.push(
+            "The node announces replicated content when it starts."
+                .dimmed()
+                .to_string(),
+        );
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Output for replica list.
+#[derive(Debug, Serialize)]
+pub struct ReplicaListOutput {
+    pub delegations: Vec<ReplicaDelegationInfo>,
+}
+
+/// A delegation held from a primary.
+#[derive(Debug, Serialize)]
+pub struct ReplicaDelegationInfo {
+    pub primary: String,
+    pub issued_at: u64,
+    pub expires_at: u64,
+    pub expired: bool,
+    pub settlement_account: Option<String>,
+}
+
+impl Render for ReplicaListOutput {
+    fn render_human(&self) -> String {
+        if self.delegations.is_empty() {
+            return "Not serving any primary's catalog.".dimmed().to_string();
+        }
 
+        let count = self.delegations.len();
+        let mut lines = vec![format!(
+            "{}",
+            format!(
+                "Serving {} {}",
+                count,
+                if count == 1 { "primary" } else { "primaries" }
+            )
+            .bold()
+        )];
+        for delegation in &self.delegations {
+            let expiry = if delegation.expired {
+                format!("expired {}", format_timestamp(delegation.expires_at))
+                    .red()
+                    .to_string()
+            } else {
+                format!("until {}", format_timestamp(delegation.expires_at))
+            };
+            lines.push(format!(
+                "  {} {}",
+                short_peer_id(&delegation.primary),
+                expiry
+            ));
+        }
         lines.join("\n")
     }
 
@@ -694,36 +3730,79 @@ impl Render for StatusOutput {
     }
 }
 
-/// Output for start command.
+/// Output for an IPFS import: the publish output plus where it came from.
 #[derive(Debug, Serialize)]
-pub struct StartOutput {
-    pub peer_id: String,
-    pub listen_addresses: Vec<String>,
-    pub connected_peers: u32,
-    pub daemon: bool,
+pub struct IpfsImportOutput {
+    pub cid: String,
+    pub source: String,
+    pub verified: bool,
+    /// CID of the record pinned back to IPFS, if any.
+    pub pin: Option<String>,
+    /// Publish output, rendered in the same format.
+    #[serde(skip)]
+    pub published: String,
 }
 
-impl Render for StartOutput {
+impl Render for IpfsImportOutput {
     fn render_human(&self) -> String {
         let mut lines = vec![
-            format!("{}", "Nodalync node started!".green().bold()),
-            format!("{} {}", "PeerId:".bold(), short_peer_id(&self.peer_id)),
+            self.published.clone(),
+            format!("{} {}", "IPFS CID:".bold(), self.cid),
         ];
+        let check = if self.verified {
+            "verified".green().to_string()
+        } else {
+            "not verified against the CID".yellow().to_string()
+        };
+        lines.push(format!("{} {} ({})", "From:".bold(), self.source, check));
+        if let Some(ref pin) = self.pin {
+            lines.push(format!("{} {}", "Pinned record:".bold(), pin));
+        }
+        lines.join("\n")
+    }
 
-        for addr in &self.listen_addresses {
-            lines.push(format!("{} {}", "Listening on:".bold(), addr));
+    fn render_json(&self) -> String {
+        // Extend the publish output with the import's fields
+        let mut value = serde_json::from_str(&self.published)
+            .unwrap_or_else(|_| serde_json::json!({ "output": self.published }));
+        if let (Some(object), Ok(serde_json::Value::Object(fields))) =
+            (value.as_object_mut(), serde_json::to_value(self))
+        {
+            object.extend(fields);
         }
+        serde_json::to_string_pretty(&value).unwrap_or_default()
+    }
+}
 
-        lines.push(format!(
-            "{} {} peers",
-            "Connected to:".bold(),
-            self.connected_peers
-        ));
+/// Output for invoice list command.
+#[derive(Debug, Serialize)]
+pub struct InvoiceListOutput {
+    pub invoices: Vec<InvoiceSummary>,
+    pub total: usize,
+}
 
-        if self.daemon {
-            lines.push(format!("{}", "Running in background.".dimmed()));
+impl Render for InvoiceListOutput {
+    fn render_human(&self) -> String {
+        if self.invoices.is_empty() {
+            return "No invoices.".dimmed().to_string();
         }
 
+        let mut lines = vec![format!("{} ({})", "Invoices:".bold(), self.total)];
+        for invoice in &self.invoices {
+            let status = if invoice.status == "paid" {
+                invoice.status.green().to_string()
+            } else {
+                invoice.status.yellow().to_string()
+            };
+            lines.push(format!(
+                "  {} {:<8} {:>22} {:<6} {}",
+                short_hash(&invoice.hash),
+                invoice.direction,
+                format_ndl(invoice.amount),
+                status,
+                invoice.memo
+            ));
+        }
         lines.join("\n")
     }
 
@@ -732,22 +3811,30 @@ impl Render for StartOutput {
     }
 }
 
-/// Output for stop command.
+/// Output for revoke command.
 #[derive(Debug, Serialize)]
-pub struct StopOutput {
-    pub success: bool,
+pub struct RevokeOutput {
+    pub peer_id: String,
+    pub own_key: bool,
+    pub newly_revoked: bool,
 }
 
-impl Render for StopOutput {
+impl Render for RevokeOutput {
     fn render_human(&self) -> String {
-        if self.success {
+        let status = if self.newly_revoked {
+            format!("{} {}", "Revoked key".green(), self.peer_id)
+        } else {
+            format!("{} {}", "Key already revoked:".yellow(), self.peer_id)
+        };
+        if self.own_key {
             format!(
                 "{}\n{}",
-                "Shutting down gracefully...".yellow(),
-                "Node stopped.".green()
+                status,
+                "This node's identity is revoked; run `nodalync init` to create a new one."
+                    .yellow()
             )
         } else {
-            "Failed to stop node.".red().to_string()
+            status
         }
     }
 
@@ -756,18 +3843,21 @@ impl Render for StopOutput {
     }
 }
 
-/// Output for delete command.
+/// Output for report command.
 #[derive(Debug, Serialize)]
-pub struct DeleteOutput {
+pub struct ReportOutput {
     pub hash: String,
+    pub reason: String,
+    pub comment: String,
 }
 
-impl Render for DeleteOutput {
+impl Render for ReportOutput {
     fn render_human(&self) -> String {
         format!(
-            "{} {} (local copy only, provenance preserved)",
-            "Deleted:".green().bold(),
-            short_hash(&self.hash)
+            "{} {} ({})",
+            "Reported".green(),
+            short_hash(&self.hash),
+            self.reason
         )
     }
 
@@ -776,120 +3866,247 @@ impl Render for DeleteOutput {
     }
 }
 
-// =============================================================================
-// Helper Functions
-// =============================================================================
+/// Reported content in the moderation queue.
+#[derive(Debug, Serialize)]
+pub struct ModerationSummary {
+    pub hash: String,
+    pub status: String,
+    pub reports: u32,
+    pub updated_at: u64,
+}
 
-/// Shorten a hash for display (first 8 + last 4 characters).
-pub fn short_hash(hash: &str) -> String {
-    if hash.len() <= 16 {
-        hash.to_string()
-    } else {
-        format!("{}...{}", &hash[..8], &hash[hash.len() - 4..])
+/// Convert a moderation entry for display.
+pub fn moderation_to_summary(entry: &ModerationEntry) -> ModerationSummary {
+    ModerationSummary {
+        hash: entry.content_hash.to_string(),
+        status: entry.status.to_string(),
+        reports: entry.report_count,
+        updated_at: entry.updated_at,
     }
 }
 
-/// Shorten a peer ID for display.
-pub fn short_peer_id(peer_id: &str) -> String {
-    if peer_id.len() <= 20 {
-        peer_id.to_string()
-    } else {
-        format!("{}...", &peer_id[..16])
+/// Output for moderation-queue command.
+#[derive(Debug, Serialize)]
+pub struct ModerationQueueOutput {
+    pub entries: Vec<ModerationSummary>,
+    pub total: usize,
+}
+
+impl Render for ModerationQueueOutput {
+    fn render_human(&self) -> String {
+        if self.entries.is_empty() {
+            return "No reported content.".dimmed().to_string();
+        }
+
+        let mut lines = vec![format!("{} ({})", "Reported content:".bold(), self.total)];
+        for entry in &self.entries {
+            let status = match entry.status.as_str() {
+                "allowed" => entry.status.green().to_string(),
+                "pending" => entry.status.yellow().to_string(),
+                _ => entry.status.red().to_string(),
+            };
+            lines.push(format!(
+                "  {} {:<11} {:>4} reports  {}",
+                short_hash(&entry.hash),
+                status,
+                entry.reports,
+                format_timestamp(entry.updated_at)
+            ));
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
     }
 }
 
-/// Format a timestamp as a human-readable date.
-fn format_timestamp(ts: u64) -> String {
-    // Simple ISO-like format: YYYY-MM-DD
-    // This is a simplified version; in production you'd use chrono
-    let secs = ts / 1000;
-    let days = secs / 86400;
-    let years_since_1970 = days / 365;
-    let year = 1970 + years_since_1970;
-    let day_of_year = days % 365;
-    let month = (day_of_year / 30) + 1;
-    let day = (day_of_year % 30) + 1;
-    format!("{:04}-{:02}-{:02}", year, month.min(12), day.min(31))
+/// One report about a piece of content.
+#[derive(Debug, Serialize)]
+pub struct ReportSummary {
+    pub reporter: String,
+    pub reason: String,
+    pub comment: String,
+    pub created_at: u64,
 }
 
-/// Format duration in seconds as human-readable.
-fn format_duration(secs: u64) -> String {
-    if secs < 60 {
-        format!("{}s", secs)
-    } else if secs < 3600 {
-        format!("{}m {}s", secs / 60, secs % 60)
-    } else {
-        format!("{}h {}m", secs / 3600, (secs % 3600) / 60)
-    }
+/// Output for reports command.
+#[derive(Debug, Serialize)]
+pub struct ContentReportsOutput {
+    pub hash: String,
+    /// Moderation status (`None` if never reported or moderated).
+    pub status: Option<String>,
+    pub reports: Vec<ReportSummary>,
 }
 
-/// Convert manifest to summary.
-pub fn manifest_to_summary(m: &Manifest) -> ManifestSummary {
-    ManifestSummary {
-        hash: m.hash.to_string(),
-        title: m.metadata.title.clone(),
-        version: m.version.number,
-        visibility: format!("{:?}", m.visibility),
-        price: m.economics.price,
-        queries: m.economics.total_queries,
-        content_type: format!("{:?}", m.content_type),
+impl Render for ContentReportsOutput {
+    fn render_human(&self) -> String {
+        let mut lines = vec![
+            format!("{} {}", "Content:".bold(), self.hash),
+            format!(
+                "{} {}",
+                "Status:".bold(),
+                self.status.as_deref().unwrap_or("not reported")
+            ),
+            format!("{} {}", "Reports:".bold(), self.reports.len()),
+        ];
+        for report in &self.reports {
+            let mut line = format!(
+                "  {} {:<9} {}",
+                format_timestamp(report.created_at),
+                report.reason,
+                short_peer_id(&report.reporter)
+            );
+            if !report.comment.is_empty() {
+                line.push_str(&format!("  \"{}\"", report.comment));
+            }
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
     }
 }
 
-/// Convert L1Summary to preview mentions.
-pub fn l1_to_preview(summary: &L1Summary) -> PreviewMentions {
-    PreviewMentions {
-        total: summary.mention_count as usize,
-        preview: summary
-            .preview_mentions
-            .iter()
-            .map(|m| m.content.clone())
-            .collect(),
+/// Output for hide and allow commands.
+#[derive(Debug, Serialize)]
+pub struct ModerationOutput {
+    pub hash: String,
+    pub status: String,
+}
+
+impl Render for ModerationOutput {
+    fn render_human(&self) -> String {
+        let action = if self.status == "allowed" {
+            "Allowed:".green()
+        } else {
+            "Hidden:".yellow()
+        };
+        format!("{} {}", action, self.hash)
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
     }
 }
 
-/// Output for earnings command.
+/// Output for simulate command.
 #[derive(Debug, Serialize)]
-pub struct EarningsOutput {
-    pub content: Vec<ContentEarning>,
-    pub total_earned: u64,
-    pub total_queries: u64,
+pub struct SimulationOutput {
+    pub seed: u64,
+    pub payments: usize,
+    pub contributors: usize,
+    pub sources: usize,
+    pub derived: usize,
+    pub batches: usize,
+    pub max_roots: usize,
+    pub max_root_weight: u32,
+    pub total_paid: u64,
+    pub total_distributed: u64,
+    pub total_settled: u64,
+    pub synthesis_fees: u64,
+    pub root_pool: u64,
+    pub rounding_dust: u64,
+    pub max_dust: u64,
+    pub gini: f64,
+    pub recipient_count: usize,
+    pub recipients: Vec<SimulationRecipient>,
+    pub violations: Vec<String>,
 }
 
-/// Individual content earning record.
+/// Per-recipient totals in a simulation report.
 #[derive(Debug, Serialize)]
-pub struct ContentEarning {
-    pub hash: String,
-    pub title: String,
-    pub queries: u64,
-    pub total_earned: u64,
-    pub price: u64,
+pub struct SimulationRecipient {
+    pub peer_id: String,
+    pub received: u64,
+    pub synthesis_fees: u64,
+    pub fair_share: u64,
+    pub share_percent: f64,
+    pub payments: u64,
 }
 
-impl Render for EarningsOutput {
+impl Render for SimulationOutput {
     fn render_human(&self) -> String {
-        if self.content.is_empty() {
-            return "No earnings yet.".dimmed().to_string();
-        }
+        let mut lines = vec![
+            format!(
+                "{} {} payments, seed {}",
+                "Economics Simulation:".bold(),
+                self.payments,
+                self.seed
+            ),
+            format!(
+                "  {} {} contributors, {} sources, {} derived (max {} roots, max weight {})",
+                "Graph:".bold(),
+                self.contributors,
+                self.sources,
+                self.derived,
+                self.max_roots,
+                self.max_root_weight
+            ),
+            format!("  {} {}", "Paid:".bold(), format_ndl(self.total_paid)),
+            format!(
+                "  {} {}",
+                "Distributed:".bold(),
+                format_ndl(self.total_distributed)
+            ),
+            format!(
+                "  {} {} in {} batches",
+                "Settled:".bold(),
+                format_ndl(self.total_settled),
+                self.batches
+            ),
+            format!(
+                "  {} {}",
+                "Synthesis fees:".bold(),
+                format_ndl(self.synthesis_fees)
+            ),
+            format!("  {} {}", "Root pool:".bold(), format_ndl(self.root_pool)),
+            format!(
+                "  {} {} tinybars (max {} per payment)",
+                "Rounding dust:".bold(),
+                self.rounding_dust,
+                self.max_dust
+            ),
+            format!(
+                "  {} {:.3} across {} recipients\n",
+                "Gini:".bold(),
+                self.gini,
+                self.recipient_count
+            ),
+        ];
 
-        let mut lines = vec![format!(
-            "{} {} from {} queries\n",
-            "Total Earnings:".green().bold(),
-            format_ndl(self.total_earned),
-            self.total_queries
-        )];
+        if !self.recipients.is_empty() {
+            lines.push(format!("{}", "Top recipients:".bold()));
+            for r in &self.recipients {
+                lines.push(format!(
+                    "  {} {} ({:.2}%) - fees {}, fair share {}, {} payments",
+                    short_peer_id(&r.peer_id).cyan(),
+                    format_ndl(r.received).green(),
+                    r.share_percent,
+                    format_ndl(r.synthesis_fees),
+                    format_ndl(r.fair_share),
+                    r.payments
+                ));
+            }
+            lines.push(String::new());
+        }
 
-        lines.push(format!("{}", "Content breakdown:".bold()));
-        for earning in &self.content {
-            let hash_short = short_hash(&earning.hash);
+        if self.violations.is_empty() {
             lines.push(format!(
-                "  {} \"{}\" - {} ({} queries @ {})",
-                hash_short.cyan(),
-                truncate_title(&earning.title, 30),
-                format_ndl(earning.total_earned).green(),
-                earning.queries,
-                format_ndl(earning.price)
+                "{} conservation, fairness, and settlement invariants held",
+                "OK:".green().bold()
+            ));
+        } else {
+            lines.push(format!(
+                "{} {} invariant violations",
+                "FAILED:".red().bold(),
+                self.violations.len()
             ));
+            for violation in &self.violations {
+                lines.push(format!("  {}", violation));
+            }
         }
 
         lines.join("\n")
@@ -1037,6 +4254,98 @@ impl Render for ChannelListOutput {
     }
 }
 
+/// Output for rebalance-channels command.
+#[derive(Debug, Serialize)]
+pub struct RebalanceOutput {
+    pub dry_run: bool,
+    pub saturated: Vec<ChannelSkewSummary>,
+    pub depleted: Vec<ChannelSkewSummary>,
+    pub actions: Vec<RebalanceActionSummary>,
+}
+
+/// Summary of a skewed payment channel.
+#[derive(Debug, Serialize)]
+pub struct ChannelSkewSummary {
+    pub peer_id: String,
+    pub my_balance: u64,
+    pub their_balance: u64,
+    pub outbound_ratio: f64,
+}
+
+/// Summary of a single rebalance action.
+#[derive(Debug, Serialize)]
+pub struct RebalanceActionSummary {
+    /// "circular" or "reopen".
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer_id: Option<String>,
+    pub amount: u64,
+    pub performed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl Render for RebalanceOutput {
+    fn render_human(&self) -> String {
+        if self.actions.is_empty() {
+            return "Channels are balanced, nothing to do.".dimmed().to_string();
+        }
+
+        let mut lines = vec![format!(
+            "{} {} saturated, {} depleted{}\n",
+            "Rebalance:".bold(),
+            self.saturated.len(),
+            self.depleted.len(),
+            if self.dry_run { " (dry run)" } else { "" }
+        )];
+
+        for skew in self.saturated.iter().chain(self.depleted.iter()) {
+            lines.push(format!(
+                "  {} my: {} / their: {} ({:.0}% outbound)",
+                short_peer_id(&skew.peer_id),
+                format_ndl(skew.my_balance),
+                format_ndl(skew.their_balance),
+                skew.outbound_ratio * 100.0
+            ));
+        }
+
+        lines.push(String::new());
+        for action in &self.actions {
+            let desc = match action.kind.as_str() {
+                "circular" => format!(
+                    "move {} from {} to {}",
+                    format_ndl(action.amount),
+                    short_peer_id(action.from.as_deref().unwrap_or_default()),
+                    short_peer_id(action.to.as_deref().unwrap_or_default())
+                ),
+                _ => format!(
+                    "reopen {} with {}",
+                    short_peer_id(action.peer_id.as_deref().unwrap_or_default()),
+                    format_ndl(action.amount)
+                ),
+            };
+            let status = if action.performed {
+                "done".green().to_string()
+            } else if let Some(ref reason) = action.reason {
+                format!("skipped: {}", reason).yellow().to_string()
+            } else {
+                "suggested".dimmed().to_string()
+            };
+            lines.push(format!("  {} [{}]", desc, status));
+        }
+
+        lines.join("\n")
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
 /// Output for search command.
 #[derive(Debug, Serialize)]
 pub struct SearchOutput {
@@ -1060,6 +4369,14 @@ pub struct SearchResult {
     /// Where this result came from: "local", "cached", or "peer".
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source: Option<String>,
+    /// Peer whose offer was kept, for peer results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer: Option<String>,
+    /// Response time of that peer, for peer results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// Whether the owner is verified (see `NetworkSearchResult`).
+    pub verified_publisher: bool,
 }
 
 impl Render for SearchOutput {
@@ -1092,7 +4409,16 @@ impl Render for SearchOutput {
             let source_str = result
                 .source
                 .as_ref()
-                .map(|s| format!(" [{}]", s).dimmed().to_string())
+                .map(|s| {
+                    let mut tags = vec![s.clone()];
+                    if let Some(ms) = result.latency_ms {
+                        tags.push(format!("{}ms", ms));
+                    }
+                    if result.verified_publisher {
+                        tags.push("verified".to_string());
+                    }
+                    format!(" [{}]", tags.join(", ")).dimmed().to_string()
+                })
                 .unwrap_or_default();
             let desc_str = result
                 .description
@@ -1151,6 +4477,7 @@ mod tests {
         let output = InitOutput {
             peer_id: "ndl1abc123".to_string(),
             config_path: "~/.nodalync/config.toml".to_string(),
+            revocation_path: "~/.nodalync/identity/revocation.json".to_string(),
         };
 
         let human = output.render(OutputFormat::Human);
@@ -0,0 +1,53 @@
+//! HTTP transport for the ops layer's event webhooks.
+//!
+//! Endpoints and their secrets are configured under `[ops.webhooks]`; the
+//! ops layer queues, signs and retries notifications, and this transport
+//! posts them.
+
+use async_trait::async_trait;
+use nodalync_ops::{WebhookRequest, WebhookTransport};
+
+/// Posts webhook notifications over HTTP(S).
+pub struct HttpWebhookTransport {
+    client: reqwest::Client,
+}
+
+impl HttpWebhookTransport {
+    /// Create a transport with its own HTTP client.
+    ///
+    /// Timeouts are applied by the ops layer.
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("nodalync/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+        Self { client }
+    }
+}
+
+impl Default for HttpWebhookTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl WebhookTransport for HttpWebhookTransport {
+    async fn post(&self, request: &WebhookRequest) -> Result<(), String> {
+        let mut builder = self
+            .client
+            .post(&request.url)
+            .header("Content-Type", "application/json")
+            .body(request.body.clone());
+        for (name, value) in request.headers() {
+            builder = builder.header(name, value);
+        }
+
+        let response = builder.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("endpoint returned status {}", response.status()));
+        }
+
+        Ok(())
+    }
+}
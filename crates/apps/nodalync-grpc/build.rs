@@ -0,0 +1,11 @@
+//! Generate the gRPC service from `proto/nodalync/v1/operations.proto`.
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Use the bundled protoc unless one is configured explicitly
+    if std::env::var_os("PROTOC").is_none() {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    }
+
+    tonic_build::configure().compile_protos(&["proto/nodalync/v1/operations.proto"], &["proto"])?;
+    Ok(())
+}
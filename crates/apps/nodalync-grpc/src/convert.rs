@@ -0,0 +1,308 @@
+//! Conversions between protocol types and their gRPC messages.
+//!
+//! Hashes travel as lowercase hex strings and peer IDs in `ndl1...` form, so
+//! request parsing failures become `INVALID_ARGUMENT` statuses here rather
+//! than operation errors.
+
+use nodalync_crypto::{peer_id_from_string, Hash, PeerId};
+use nodalync_ops::{ChannelCloseStatus, OpsEvent};
+use nodalync_types::{
+    Channel, ChannelState, ContentType, L1Summary, Manifest, Metadata, Visibility,
+};
+use nodalync_wire::VersionInfo;
+use tonic::Status;
+
+use crate::proto;
+
+/// Parse a hex content hash.
+pub fn parse_hash(s: &str) -> Result<Hash, Status> {
+    let invalid = || Status::invalid_argument(format!("invalid hash: {}", s));
+    if s.len() != 64 || !s.is_ascii() {
+        return Err(invalid());
+    }
+
+    let mut bytes = [0u8; 32];
+    for (byte, pair) in bytes.iter_mut().zip(s.as_bytes().chunks(2)) {
+        let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+        *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+    }
+    Ok(Hash(bytes))
+}
+
+/// Parse a list of hex content hashes.
+pub fn parse_hashes(hashes: &[String]) -> Result<Vec<Hash>, Status> {
+    hashes.iter().map(|h| parse_hash(h)).collect()
+}
+
+/// Parse an `ndl1...` peer ID.
+pub fn parse_peer_id(s: &str) -> Result<PeerId, Status> {
+    peer_id_from_string(s).map_err(|e| Status::invalid_argument(format!("invalid peer ID: {}", e)))
+}
+
+/// Convert a wire visibility value.
+pub fn parse_visibility(value: i32) -> Result<Visibility, Status> {
+    let visibility = proto::Visibility::try_from(value)
+        .map_err(|_| Status::invalid_argument(format!("invalid visibility: {}", value)))?;
+    Ok(match visibility {
+        proto::Visibility::Private => Visibility::Private,
+        proto::Visibility::Unlisted => Visibility::Unlisted,
+        proto::Visibility::Shared => Visibility::Shared,
+        proto::Visibility::Offline => Visibility::Offline,
+    })
+}
+
+/// Build content metadata from a request, sized to `content_size`.
+pub fn parse_metadata(
+    metadata: Option<proto::Metadata>,
+    content_size: usize,
+) -> Result<Metadata, Status> {
+    let metadata = metadata.ok_or_else(|| Status::invalid_argument("metadata is required"))?;
+    if metadata.title.trim().is_empty() {
+        return Err(Status::invalid_argument("title cannot be empty"));
+    }
+
+    let mut result = Metadata::new(metadata.title, content_size as u64);
+    result.description = metadata.description;
+    result.tags = metadata.tags;
+    result.mime_type = metadata.mime_type;
+    Ok(result)
+}
+
+impl From<Visibility> for proto::Visibility {
+    fn from(visibility: Visibility) -> Self {
+        match visibility {
+            Visibility::Unlisted => Self::Unlisted,
+            Visibility::Shared => Self::Shared,
+            Visibility::Offline => Self::Offline,
+            // Report anything newer as its most restrictive reading
+            _ => Self::Private,
+        }
+    }
+}
+
+impl From<ContentType> for proto::ContentType {
+    fn from(content_type: ContentType) -> Self {
+        match content_type {
+            ContentType::L1 => Self::L1,
+            ContentType::L2 => Self::L2,
+            ContentType::L3 => Self::L3,
+            ContentType::Collection => Self::Collection,
+            _ => Self::L0,
+        }
+    }
+}
+
+impl From<ChannelState> for proto::ChannelState {
+    fn from(state: ChannelState) -> Self {
+        match state {
+            ChannelState::Opening => Self::Opening,
+            ChannelState::Open => Self::Open,
+            ChannelState::Closing => Self::Closing,
+            ChannelState::Disputed => Self::Disputed,
+            _ => Self::Closed,
+        }
+    }
+}
+
+impl From<&Manifest> for proto::Manifest {
+    fn from(manifest: &Manifest) -> Self {
+        Self {
+            hash: manifest.hash.to_string(),
+            content_type: proto::ContentType::from(manifest.content_type).into(),
+            owner: manifest.owner.to_string(),
+            version: manifest.version.number,
+            previous: manifest.version.previous.map(|h| h.to_string()),
+            version_root: manifest.version.root.to_string(),
+            visibility: proto::Visibility::from(manifest.visibility).into(),
+            title: manifest.metadata.title.clone(),
+            description: manifest.metadata.description.clone(),
+            tags: manifest.metadata.tags.clone(),
+            content_size: manifest.metadata.content_size,
+            price: manifest.economics.price,
+            total_queries: manifest.economics.total_queries,
+            provenance_depth: manifest.provenance.depth,
+            created_at: manifest.created_at,
+            updated_at: manifest.updated_at,
+        }
+    }
+}
+
+impl From<&L1Summary> for proto::L1Summary {
+    fn from(summary: &L1Summary) -> Self {
+        Self {
+            l0_hash: summary.l0_hash.to_string(),
+            mention_count: summary.mention_count,
+            preview_mentions: summary
+                .preview_mentions
+                .iter()
+                .map(|m| proto::Mention {
+                    id: m.id.to_string(),
+                    content: m.content.clone(),
+                    entities: m.entities.clone(),
+                })
+                .collect(),
+            primary_topics: summary.primary_topics.clone(),
+            summary: summary.summary.clone(),
+        }
+    }
+}
+
+impl From<&VersionInfo> for proto::VersionInfo {
+    fn from(version: &VersionInfo) -> Self {
+        Self {
+            hash: version.hash.to_string(),
+            number: version.number,
+            timestamp: version.timestamp,
+            visibility: proto::Visibility::from(version.visibility).into(),
+            price: version.price,
+        }
+    }
+}
+
+impl From<&Channel> for proto::Channel {
+    fn from(channel: &Channel) -> Self {
+        Self {
+            channel_id: channel.channel_id.to_string(),
+            peer_id: channel.peer_id.to_string(),
+            state: proto::ChannelState::from(channel.state).into(),
+            my_balance: channel.my_balance,
+            their_balance: channel.their_balance,
+            nonce: channel.nonce,
+            pending_payments: channel.pending_payments.len() as u32,
+        }
+    }
+}
+
+impl From<ChannelCloseStatus> for proto::ChannelCloseStatus {
+    fn from(status: ChannelCloseStatus) -> Self {
+        match status {
+            ChannelCloseStatus::Closed => Self::Closed,
+            ChannelCloseStatus::Unresponsive => Self::Unresponsive,
+            ChannelCloseStatus::Failed => Self::Failed,
+        }
+    }
+}
+
+/// Convert an operations event, or `None` for events this API doesn't
+/// carry yet.
+pub fn ops_event(event: &OpsEvent) -> Option<proto::OpsEvent> {
+    use proto::ops_event::Event;
+
+    let event = match event {
+        OpsEvent::ChannelCloseStarted { total } => {
+            Event::ChannelCloseStarted(proto::ChannelCloseStarted {
+                total: *total as u64,
+            })
+        }
+        OpsEvent::ChannelCloseProgress {
+            peer,
+            status,
+            completed,
+            total,
+        } => Event::ChannelCloseProgress(proto::ChannelCloseProgress {
+            peer_id: peer.to_string(),
+            status: proto::ChannelCloseStatus::from(*status).into(),
+            completed: *completed as u64,
+            total: *total as u64,
+        }),
+        OpsEvent::ChannelCloseFinished {
+            closed,
+            unresponsive,
+            failed,
+        } => Event::ChannelCloseFinished(proto::ChannelCloseFinished {
+            closed: *closed as u64,
+            unresponsive: *unresponsive as u64,
+            failed: *failed as u64,
+        }),
+        OpsEvent::SettlementBatch { batch_id } => Event::SettlementBatch(proto::SettlementBatch {
+            batch_id: batch_id.to_string(),
+        }),
+        OpsEvent::SettlementToppedUp { amount, balance } => {
+            Event::SettlementToppedUp(proto::SettlementToppedUp {
+                amount: *amount,
+                balance: *balance,
+            })
+        }
+        OpsEvent::ContentFlagged {
+            hash,
+            score,
+            reasons,
+        } => Event::ContentFlagged(proto::ContentFlagged {
+            hash: hash.to_string(),
+            score: u32::from(*score),
+            reasons: reasons.clone(),
+        }),
+        OpsEvent::FraudProven {
+            provider,
+            content_hash,
+            proof_hash,
+        } => Event::FraudProven(proto::FraudProven {
+            provider: provider.to_string(),
+            content_hash: content_hash.to_string(),
+            proof_hash: proof_hash.to_string(),
+        }),
+        OpsEvent::ClockSkewExceeded {
+            offset_ms,
+            tolerance_ms,
+        } => Event::ClockSkewExceeded(proto::ClockSkewExceeded {
+            offset_ms: *offset_ms,
+            tolerance_ms: *tolerance_ms,
+        }),
+        OpsEvent::TopUpCapReached {
+            balance,
+            deposited_today,
+            daily_cap,
+        } => Event::TopUpCapReached(proto::TopUpCapReached {
+            balance: *balance,
+            deposited_today: *deposited_today,
+            daily_cap: *daily_cap,
+        }),
+        _ => return None,
+    };
+
+    Some(proto::OpsEvent { event: Some(event) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::content_hash;
+
+    #[test]
+    fn test_parse_hash_roundtrip() {
+        let hash = content_hash(b"grpc");
+        assert_eq!(parse_hash(&hash.to_string()).unwrap(), hash);
+
+        for bad in ["", "abc", &"zz".repeat(32), &"é".repeat(32)] {
+            let status = parse_hash(bad).unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        }
+    }
+
+    #[test]
+    fn test_parse_visibility() {
+        assert_eq!(
+            parse_visibility(proto::Visibility::Shared.into()).unwrap(),
+            Visibility::Shared
+        );
+        assert!(parse_visibility(42).is_err());
+    }
+
+    #[test]
+    fn test_ops_event() {
+        let event = ops_event(&OpsEvent::SettlementToppedUp {
+            amount: 10,
+            balance: 30,
+        })
+        .unwrap();
+        assert_eq!(
+            event.event,
+            Some(proto::ops_event::Event::SettlementToppedUp(
+                proto::SettlementToppedUp {
+                    amount: 10,
+                    balance: 30
+                }
+            ))
+        );
+    }
+}
@@ -0,0 +1,87 @@
+//! Error types for the gRPC server, and the mapping from operation errors
+//! to gRPC status codes.
+
+use nodalync_ops::OpsError;
+use nodalync_types::ErrorCode;
+use thiserror::Error;
+use tonic::metadata::{MetadataMap, MetadataValue};
+use tonic::{Code, Status};
+
+/// Metadata key carrying the protocol error code (spec Appendix C).
+pub const ERROR_CODE_METADATA_KEY: &str = "nodalync-error-code";
+
+/// Result type for gRPC server operations.
+pub type GrpcResult<T> = Result<T, GrpcError>;
+
+/// Error types for running the gRPC server.
+#[derive(Debug, Error)]
+pub enum GrpcError {
+    /// The server transport failed (bind, accept, HTTP/2).
+    #[error("transport error: {0}")]
+    Transport(#[from] tonic::transport::Error),
+
+    /// I/O error.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// The gRPC status code for a protocol error code.
+pub fn status_code(code: ErrorCode) -> Code {
+    match code {
+        ErrorCode::NotFound
+        | ErrorCode::VersionNotFound
+        | ErrorCode::ChannelNotFound
+        | ErrorCode::PeerNotFound => Code::NotFound,
+        ErrorCode::AccessDenied => Code::PermissionDenied,
+        ErrorCode::PaymentRequired
+        | ErrorCode::PaymentInvalid
+        | ErrorCode::ChallengeRequired
+        | ErrorCode::ChannelClosed
+        | ErrorCode::InsufficientBalance
+        | ErrorCode::InvalidNonce => Code::FailedPrecondition,
+        ErrorCode::RateLimited => Code::ResourceExhausted,
+        ErrorCode::ConnectionFailed => Code::Unavailable,
+        ErrorCode::Timeout => Code::DeadlineExceeded,
+        ErrorCode::InternalError => Code::Internal,
+        ErrorCode::InvalidSignature => Code::InvalidArgument,
+        _ if code.is_validation_error() => Code::InvalidArgument,
+        _ => Code::Internal,
+    }
+}
+
+/// Convert an operation error to a status, with the protocol error code in
+/// the [`ERROR_CODE_METADATA_KEY`] metadata.
+pub fn ops_status(error: OpsError) -> Status {
+    let code = error.error_code();
+    let mut metadata = MetadataMap::new();
+    if let Ok(value) = MetadataValue::try_from(code.to_string()) {
+        metadata.insert(ERROR_CODE_METADATA_KEY, value);
+    }
+    Status::with_metadata(status_code(code), error.to_string(), metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::Hash;
+
+    #[test]
+    fn test_ops_status() {
+        let status = ops_status(OpsError::NotFound(Hash([0u8; 32])));
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(
+            status.metadata().get(ERROR_CODE_METADATA_KEY).unwrap(),
+            "NOT_FOUND"
+        );
+
+        assert_eq!(
+            ops_status(OpsError::AccessDenied).code(),
+            Code::PermissionDenied
+        );
+        assert_eq!(
+            status_code(ErrorCode::InvalidManifest),
+            Code::InvalidArgument
+        );
+        assert_eq!(status_code(ErrorCode::RateLimited), Code::ResourceExhausted);
+    }
+}
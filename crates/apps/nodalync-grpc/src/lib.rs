@@ -0,0 +1,49 @@
+//! gRPC server for Nodalync node operations.
+//!
+//! This crate exposes a running node's operations (spec §7) as the
+//! `nodalync.v1.Operations` gRPC service, so services in any language can
+//! integrate with a node through generated, strongly typed clients.
+//!
+//! The service definition is committed at
+//! `proto/nodalync/v1/operations.proto`; generate clients from it with
+//! `protoc` or `buf`. Rust clients can use [`proto::operations_client`].
+//!
+//! # Overview
+//!
+//! - Content: create, extract L1, publish, unpublish, update, derive,
+//!   reference L3 as L0, set visibility
+//! - Query: preview, and query with the content streamed back in
+//!   [`service::QUERY_CHUNK_SIZE`] chunks after a header message
+//! - Versions and manifests
+//! - Channels: open, close, dispute
+//! - Settlement: trigger a batch
+//! - Events: a server stream of [`nodalync_ops::OpsEvent`]s
+//!
+//! Errors are returned as gRPC statuses; the protocol error code is in the
+//! [`error::ERROR_CODE_METADATA_KEY`] metadata.
+//!
+//! # Usage
+//!
+//! The server is typically started via the CLI:
+//!
+//! ```bash
+//! nodalync grpc-server --listen 127.0.0.1:50051
+//! ```
+
+// Handlers return `tonic::Status` by value, as the generated service does
+#![allow(clippy::result_large_err)]
+
+pub mod convert;
+pub mod error;
+pub mod server;
+pub mod service;
+
+/// Generated `nodalync.v1` messages, server and client.
+#[allow(clippy::large_enum_variant)]
+pub mod proto {
+    tonic::include_proto!("nodalync.v1");
+}
+
+pub use error::{GrpcError, GrpcResult};
+pub use server::{serve, serve_listener, DEFAULT_LISTEN_ADDR};
+pub use service::OperationsService;
@@ -0,0 +1,44 @@
+//! Running the gRPC server.
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use nodalync_ops::DefaultNodeOperations;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Server;
+use tracing::info;
+
+use crate::error::GrpcResult;
+use crate::service::OperationsService;
+
+/// Default listen address: loopback only, since the API can spend funds.
+pub const DEFAULT_LISTEN_ADDR: &str = "127.0.0.1:50051";
+
+/// Serve `ops` on `addr` until `shutdown` completes.
+pub async fn serve(
+    ops: Arc<DefaultNodeOperations>,
+    addr: SocketAddr,
+    shutdown: impl Future<Output = ()> + Send,
+) -> GrpcResult<()> {
+    let listener = TcpListener::bind(addr).await?;
+    serve_listener(ops, listener, shutdown).await
+}
+
+/// Serve `ops` on an already bound listener until `shutdown` completes.
+pub async fn serve_listener(
+    ops: Arc<DefaultNodeOperations>,
+    listener: TcpListener,
+    shutdown: impl Future<Output = ()> + Send,
+) -> GrpcResult<()> {
+    info!(addr = %listener.local_addr()?, "gRPC server listening");
+
+    Server::builder()
+        .add_service(OperationsService::new(ops).into_server())
+        .serve_with_incoming_shutdown(TcpListenerStream::new(listener), shutdown)
+        .await?;
+
+    info!("gRPC server stopped");
+    Ok(())
+}
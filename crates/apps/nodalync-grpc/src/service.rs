@@ -0,0 +1,530 @@
+//! The `nodalync.v1.Operations` service.
+//!
+//! Each RPC maps onto the matching `DefaultNodeOperations` method. The
+//! channel-negotiation and L2 build/merge operations are not exposed: the
+//! former are driven by peers over the network, and the latter need entity
+//! graphs that have no stable wire form yet.
+
+use std::pin::Pin;
+use std::sync::Arc;
+
+use nodalync_ops::{CloseResult, DefaultNodeOperations};
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+use tracing::warn;
+
+use crate::convert::{
+    ops_event, parse_hash, parse_hashes, parse_metadata, parse_peer_id, parse_visibility,
+};
+use crate::error::ops_status;
+use crate::proto::{self, operations_server::Operations};
+
+/// Size of the data chunks a query response is streamed in.
+pub const QUERY_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Stream of server-sent messages.
+type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// gRPC service over a node's operations.
+///
+/// Operations are shared without a lock, so RPCs run concurrently. Signing
+/// operations (channel close and dispute) use the private key set on the
+/// operations.
+#[derive(Clone)]
+pub struct OperationsService {
+    ops: Arc<DefaultNodeOperations>,
+}
+
+impl OperationsService {
+    /// Create a service over shared operations.
+    pub fn new(ops: Arc<DefaultNodeOperations>) -> Self {
+        Self { ops }
+    }
+
+    /// Wrap the service for a tonic server.
+    pub fn into_server(self) -> proto::operations_server::OperationsServer<Self> {
+        proto::operations_server::OperationsServer::new(self)
+    }
+
+    fn private_key(&self) -> Result<&nodalync_crypto::PrivateKey, Status> {
+        self.ops
+            .private_key()
+            .ok_or_else(|| Status::failed_precondition("node identity is not loaded"))
+    }
+}
+
+#[tonic::async_trait]
+impl Operations for OperationsService {
+    async fn create(
+        &self,
+        request: Request<proto::CreateRequest>,
+    ) -> Result<Response<proto::HashReply>, Status> {
+        let request = request.into_inner();
+        let metadata = parse_metadata(request.metadata, request.content.len())?;
+        let hash = self
+            .ops
+            .create_content(&request.content, metadata)
+            .map_err(ops_status)?;
+        Ok(Response::new(proto::HashReply {
+            hash: hash.to_string(),
+        }))
+    }
+
+    async fn extract_l1(
+        &self,
+        request: Request<proto::HashRequest>,
+    ) -> Result<Response<proto::L1Summary>, Status> {
+        let hash = parse_hash(&request.into_inner().hash)?;
+        let summary = self.ops.extract_l1_summary(&hash).map_err(ops_status)?;
+        Ok(Response::new((&summary).into()))
+    }
+
+    async fn publish(
+        &self,
+        request: Request<proto::PublishRequest>,
+    ) -> Result<Response<proto::Empty>, Status> {
+        let request = request.into_inner();
+        let hash = parse_hash(&request.hash)?;
+        let visibility = parse_visibility(request.visibility)?;
+        self.ops
+            .publish_content(&hash, visibility, request.price)
+            .await
+            .map_err(ops_status)?;
+        Ok(Response::new(proto::Empty {}))
+    }
+
+    async fn unpublish(
+        &self,
+        request: Request<proto::HashRequest>,
+    ) -> Result<Response<proto::Empty>, Status> {
+        let hash = parse_hash(&request.into_inner().hash)?;
+        self.ops
+            .unpublish_content(&hash)
+            .await
+            .map_err(ops_status)?;
+        Ok(Response::new(proto::Empty {}))
+    }
+
+    async fn update(
+        &self,
+        request: Request<proto::UpdateRequest>,
+    ) -> Result<Response<proto::HashReply>, Status> {
+        let request = request.into_inner();
+        let old_hash = parse_hash(&request.hash)?;
+        let metadata = parse_metadata(request.metadata, request.content.len())?;
+        let hash = self
+            .ops
+            .update_content(&old_hash, &request.content, metadata)
+            .map_err(ops_status)?;
+        Ok(Response::new(proto::HashReply {
+            hash: hash.to_string(),
+        }))
+    }
+
+    async fn derive(
+        &self,
+        request: Request<proto::DeriveRequest>,
+    ) -> Result<Response<proto::HashReply>, Status> {
+        let request = request.into_inner();
+        let sources = parse_hashes(&request.sources)?;
+        let metadata = parse_metadata(request.metadata, request.insight.len())?;
+        let hash = self
+            .ops
+            .derive_content(&sources, &request.insight, metadata)
+            .map_err(ops_status)?;
+        Ok(Response::new(proto::HashReply {
+            hash: hash.to_string(),
+        }))
+    }
+
+    async fn reference_l3_as_l0(
+        &self,
+        request: Request<proto::HashRequest>,
+    ) -> Result<Response<proto::HashReply>, Status> {
+        let l3_hash = parse_hash(&request.into_inner().hash)?;
+        let hash = self.ops.reference_l3_as_l0(&l3_hash).map_err(ops_status)?;
+        Ok(Response::new(proto::HashReply {
+            hash: hash.to_string(),
+        }))
+    }
+
+    async fn preview(
+        &self,
+        request: Request<proto::HashRequest>,
+    ) -> Result<Response<proto::PreviewReply>, Status> {
+        let hash = parse_hash(&request.into_inner().hash)?;
+        let preview = self.ops.preview_content(&hash).await.map_err(ops_status)?;
+        Ok(Response::new(proto::PreviewReply {
+            manifest: Some((&preview.manifest).into()),
+            l1_summary: Some((&preview.l1_summary).into()),
+            forks: preview.forks.iter().map(|h| h.to_string()).collect(),
+        }))
+    }
+
+    type QueryStream = ResponseStream<proto::QueryChunk>;
+
+    async fn query(
+        &self,
+        request: Request<proto::QueryRequest>,
+    ) -> Result<Response<Self::QueryStream>, Status> {
+        let request = request.into_inner();
+        let hash = parse_hash(&request.hash)?;
+        let payment = match request.payment {
+            Some(payment) => payment,
+            None => {
+                let preview = self.ops.preview_content(&hash).await.map_err(ops_status)?;
+                self.ops.quote_price(&preview).await
+            }
+        };
+
+        let response = self
+            .ops
+            .query_content(&hash, payment, None)
+            .await
+            .map_err(ops_status)?;
+
+        let header = proto::QueryChunk {
+            chunk: Some(proto::query_chunk::Chunk::Header(proto::QueryHeader {
+                manifest: Some((&response.manifest).into()),
+                price_paid: response.receipt.amount,
+                content_size: response.content.len() as u64,
+            })),
+        };
+        let data: Vec<proto::QueryChunk> = response
+            .content
+            .chunks(QUERY_CHUNK_SIZE)
+            .map(|chunk| proto::QueryChunk {
+                chunk: Some(proto::query_chunk::Chunk::Data(chunk.to_vec())),
+            })
+            .collect();
+
+        let stream = tokio_stream::iter(std::iter::once(header).chain(data).map(Ok));
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn get_versions(
+        &self,
+        request: Request<proto::HashRequest>,
+    ) -> Result<Response<proto::VersionsReply>, Status> {
+        let root = parse_hash(&request.into_inner().hash)?;
+        let versions = self.ops.get_content_versions(&root).map_err(ops_status)?;
+        Ok(Response::new(proto::VersionsReply {
+            versions: versions.iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_manifest(
+        &self,
+        request: Request<proto::HashRequest>,
+    ) -> Result<Response<proto::Manifest>, Status> {
+        let hash = parse_hash(&request.into_inner().hash)?;
+        let manifest = self
+            .ops
+            .get_content_manifest(&hash)
+            .map_err(ops_status)?
+            .ok_or_else(|| Status::not_found(format!("manifest not found: {}", hash)))?;
+        Ok(Response::new((&manifest).into()))
+    }
+
+    async fn set_visibility(
+        &self,
+        request: Request<proto::SetVisibilityRequest>,
+    ) -> Result<Response<proto::Empty>, Status> {
+        let request = request.into_inner();
+        let hash = parse_hash(&request.hash)?;
+        let visibility = parse_visibility(request.visibility)?;
+        self.ops
+            .set_content_visibility(&hash, visibility)
+            .map_err(ops_status)?;
+        Ok(Response::new(proto::Empty {}))
+    }
+
+    async fn open_channel(
+        &self,
+        request: Request<proto::OpenChannelRequest>,
+    ) -> Result<Response<proto::Channel>, Status> {
+        let request = request.into_inner();
+        let peer = parse_peer_id(&request.peer_id)?;
+        let channel = self
+            .ops
+            .open_payment_channel(&peer, request.deposit)
+            .await
+            .map_err(ops_status)?;
+        Ok(Response::new((&channel).into()))
+    }
+
+    async fn close_channel(
+        &self,
+        request: Request<proto::PeerRequest>,
+    ) -> Result<Response<proto::CloseChannelReply>, Status> {
+        let peer = parse_peer_id(&request.into_inner().peer_id)?;
+        let private_key = self.private_key()?;
+        let result = self
+            .ops
+            .close_payment_channel(&peer, private_key)
+            .await
+            .map_err(ops_status)?;
+
+        use proto::close_channel_reply::Result as Reply;
+        let reply = match result {
+            CloseResult::Success {
+                transaction_id,
+                final_balances: (my_balance, their_balance),
+            } => Reply::ClosedOnChain(proto::ClosedOnChain {
+                transaction_id,
+                my_balance,
+                their_balance,
+            }),
+            CloseResult::SuccessOffChain {
+                final_balances: (my_balance, their_balance),
+            } => Reply::ClosedOffChain(proto::ClosedOffChain {
+                my_balance,
+                their_balance,
+            }),
+            CloseResult::PeerUnresponsive { suggestion } => {
+                Reply::PeerUnresponsive(proto::PeerUnresponsive { suggestion })
+            }
+            CloseResult::OnChainFailed { error } => {
+                return Err(Status::aborted(format!("on-chain close failed: {}", error)));
+            }
+        };
+        Ok(Response::new(proto::CloseChannelReply {
+            result: Some(reply),
+        }))
+    }
+
+    async fn dispute_channel(
+        &self,
+        request: Request<proto::PeerRequest>,
+    ) -> Result<Response<proto::DisputeChannelReply>, Status> {
+        let peer = parse_peer_id(&request.into_inner().peer_id)?;
+        let private_key = self.private_key()?;
+        let transaction_id = self
+            .ops
+            .dispute_payment_channel(&peer, private_key)
+            .await
+            .map_err(ops_status)?;
+        Ok(Response::new(proto::DisputeChannelReply { transaction_id }))
+    }
+
+    async fn trigger_settlement(
+        &self,
+        _request: Request<proto::Empty>,
+    ) -> Result<Response<proto::TriggerSettlementReply>, Status> {
+        let batch_id = self
+            .ops
+            .trigger_settlement_batch()
+            .await
+            .map_err(ops_status)?;
+        Ok(Response::new(proto::TriggerSettlementReply {
+            batch_id: batch_id.map(|h| h.to_string()),
+        }))
+    }
+
+    async fn get_peer_id(
+        &self,
+        _request: Request<proto::Empty>,
+    ) -> Result<Response<proto::PeerIdReply>, Status> {
+        Ok(Response::new(proto::PeerIdReply {
+            peer_id: self.ops.peer_id().to_string(),
+        }))
+    }
+
+    type SubscribeEventsStream = ResponseStream<proto::OpsEvent>;
+
+    async fn subscribe_events(
+        &self,
+        _request: Request<proto::Empty>,
+    ) -> Result<Response<Self::SubscribeEventsStream>, Status> {
+        let stream =
+            BroadcastStream::new(self.ops.subscribe_events()).filter_map(|event| match event {
+                Ok(event) => ops_event(&event).map(Ok),
+                Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                    // A slow client misses events rather than stalling ops
+                    warn!(skipped, "gRPC event subscriber lagged");
+                    None
+                }
+            });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use tempfile::TempDir;
+    use tokio::net::TcpListener;
+
+    use crate::proto::operations_client::OperationsClient;
+
+    /// Start a server over fresh operations and connect a client to it.
+    async fn start(
+        temp_dir: &TempDir,
+    ) -> (
+        Arc<DefaultNodeOperations>,
+        OperationsClient<tonic::transport::Channel>,
+    ) {
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops =
+            DefaultNodeOperations::with_defaults(state, peer_id_from_public_key(&public_key));
+        ops.set_private_key(private_key);
+        let ops = Arc::new(ops);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(crate::serve_listener(
+            Arc::clone(&ops),
+            listener,
+            std::future::pending(),
+        ));
+
+        let client = OperationsClient::connect(format!("http://{}", addr))
+            .await
+            .unwrap();
+        (ops, client)
+    }
+
+    fn metadata(title: &str) -> Option<proto::Metadata> {
+        Some(proto::Metadata {
+            title: title.to_string(),
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_create_publish_preview_query() {
+        let temp_dir = TempDir::new().unwrap();
+        let (ops, mut client) = start(&temp_dir).await;
+
+        // Large enough to span several chunks
+        let content = "Knowledge served over gRPC. ".repeat(5_000).into_bytes();
+        let hash = client
+            .create(proto::CreateRequest {
+                content: content.clone(),
+                metadata: metadata("gRPC"),
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .hash;
+        client
+            .publish(proto::PublishRequest {
+                hash: hash.clone(),
+                visibility: proto::Visibility::Shared.into(),
+                price: 0,
+            })
+            .await
+            .unwrap();
+
+        let preview = client
+            .preview(proto::HashRequest { hash: hash.clone() })
+            .await
+            .unwrap()
+            .into_inner();
+        let manifest = preview.manifest.unwrap();
+        assert_eq!(manifest.title, "gRPC");
+        assert_eq!(manifest.owner, ops.peer_id().to_string());
+        assert_eq!(manifest.visibility(), proto::Visibility::Shared);
+
+        let mut stream = client
+            .query(proto::QueryRequest {
+                hash: hash.clone(),
+                payment: None,
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        let Some(proto::query_chunk::Chunk::Header(header)) =
+            stream.message().await.unwrap().unwrap().chunk
+        else {
+            panic!("expected a header first");
+        };
+        assert_eq!(header.content_size, content.len() as u64);
+
+        let mut received = Vec::new();
+        let mut chunks = 0;
+        while let Some(chunk) = stream.message().await.unwrap() {
+            let Some(proto::query_chunk::Chunk::Data(data)) = chunk.chunk else {
+                panic!("expected data");
+            };
+            assert!(data.len() <= QUERY_CHUNK_SIZE);
+            received.extend(data);
+            chunks += 1;
+        }
+        assert_eq!(received, content);
+        assert!(chunks > 1);
+
+        let versions = client
+            .get_versions(proto::HashRequest { hash })
+            .await
+            .unwrap()
+            .into_inner()
+            .versions;
+        assert_eq!(versions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_errors_map_to_status() {
+        let temp_dir = TempDir::new().unwrap();
+        let (_ops, mut client) = start(&temp_dir).await;
+
+        let status = client
+            .preview(proto::HashRequest {
+                hash: "not-a-hash".to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let status = client
+            .get_manifest(proto::HashRequest {
+                hash: "00".repeat(32),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+
+        let status = client
+            .create(proto::CreateRequest {
+                content: b"untitled".to_vec(),
+                metadata: None,
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_events() {
+        let temp_dir = TempDir::new().unwrap();
+        let (ops, mut client) = start(&temp_dir).await;
+
+        let mut events = client
+            .subscribe_events(proto::Empty {})
+            .await
+            .unwrap()
+            .into_inner();
+
+        // Closing no channels still reports the batch
+        let private_key = ops.private_key().unwrap().clone();
+        ops.close_all_payment_channels(&private_key).await.unwrap();
+
+        let event = events.message().await.unwrap().unwrap();
+        assert_eq!(
+            event.event,
+            Some(proto::ops_event::Event::ChannelCloseStarted(
+                proto::ChannelCloseStarted { total: 0 }
+            ))
+        );
+        let event = events.message().await.unwrap().unwrap();
+        assert!(matches!(
+            event.event,
+            Some(proto::ops_event::Event::ChannelCloseFinished(_))
+        ));
+    }
+}
@@ -80,3 +80,9 @@ impl McpError {
         }
     }
 }
+
+impl From<&McpError> for ErrorCode {
+    fn from(error: &McpError) -> Self {
+        error.error_code()
+    }
+}
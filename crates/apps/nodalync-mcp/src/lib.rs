@@ -5,10 +5,11 @@
 //!
 //! # Overview
 //!
-//! The MCP server exposes two main tools:
+//! The main tools exposed by the MCP server are:
 //!
 //! - **query_knowledge**: Query content from the network with payment
 //! - **list_sources**: Browse available content by topic
+//! - **list_recommended**: Content suggested from your knowledge graph and query history
 //!
 //! And one resource:
 //!
@@ -43,14 +44,21 @@
 //! - Each query shows cost preview before execution
 //! - Queries auto-approve if under threshold (default 0.01 HBAR)
 //! - Queries are rejected if they would exceed remaining budget
+//!
+//! # Metrics
+//!
+//! With the `otlp` feature and a metrics endpoint, tool call counts and
+//! latency and budget spend are exported over OTLP (see [`metrics`]).
 
 pub mod budget;
 pub mod error;
+pub mod metrics;
 pub mod server;
 pub mod tools;
 
 pub use budget::{BudgetStatus, BudgetTracker};
 pub use error::{McpError, McpResult};
+pub use metrics::{McpMetrics, MetricsConfig};
 pub use server::NodalyncMcpServer;
 pub use tools::{
     ContentEarnings, DeleteContentInput, DeleteContentOutput, GetEarningsInput, GetEarningsOutput,
@@ -0,0 +1,241 @@
+//! OpenTelemetry metrics for the MCP server.
+//!
+//! When an OTLP endpoint is configured (and the crate is built with the
+//! `otlp` feature), the server exports:
+//!
+//! | Metric | Type | Attributes |
+//! |--------|------|------------|
+//! | `nodalync.mcp.tool.calls` | counter | `tool`, `outcome` (`ok`/`error`) |
+//! | `nodalync.mcp.tool.duration` | histogram (seconds) | `tool`, `outcome` |
+//! | `nodalync.mcp.budget.spent` | counter (tinybars) | `source` (`tool`/`resource`/`invoice`) |
+//! | `nodalync.mcp.budget.refunded` | counter (tinybars) | `source` |
+//!
+//! Without an endpoint every recording method is a no-op.
+
+use std::time::Duration;
+
+use nodalync_types::Amount;
+
+/// Where and how often metrics are exported.
+#[derive(Debug, Clone)]
+pub struct MetricsConfig {
+    /// Collector metrics endpoint (e.g. `http://localhost:4318/v1/metrics`).
+    /// Metrics are disabled when unset.
+    pub endpoint: Option<String>,
+    /// Service name the metrics are reported under.
+    pub service_name: String,
+    /// Seconds between exports.
+    pub export_interval_secs: u64,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            service_name: "nodalync-mcp".to_string(),
+            export_interval_secs: 60,
+        }
+    }
+}
+
+/// Instruments recording server activity.
+#[derive(Clone, Default)]
+pub struct McpMetrics {
+    #[cfg(feature = "otlp")]
+    exporter: Option<std::sync::Arc<Exporter>>,
+}
+
+impl McpMetrics {
+    /// Metrics that record nothing.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Start exporting as configured.
+    ///
+    /// Fails if an endpoint is set but invalid, or the crate was built
+    /// without the `otlp` feature.
+    pub fn init(config: &MetricsConfig) -> Result<Self, String> {
+        let Some(ref endpoint) = config.endpoint else {
+            return Ok(Self::disabled());
+        };
+
+        #[cfg(feature = "otlp")]
+        {
+            let exporter = Exporter::new(config, endpoint)?;
+            tracing::info!(endpoint = %endpoint, "Exporting MCP metrics over OTLP");
+            Ok(Self {
+                exporter: Some(std::sync::Arc::new(exporter)),
+            })
+        }
+        #[cfg(not(feature = "otlp"))]
+        {
+            let _ = endpoint;
+            Err("metrics export needs a build with the `otlp` feature".to_string())
+        }
+    }
+
+    /// Whether metrics are being exported.
+    pub fn is_enabled(&self) -> bool {
+        #[cfg(feature = "otlp")]
+        {
+            self.exporter.is_some()
+        }
+        #[cfg(not(feature = "otlp"))]
+        {
+            false
+        }
+    }
+
+    /// Record a finished tool call.
+    pub fn record_tool_call(&self, tool: &str, ok: bool, elapsed: Duration) {
+        #[cfg(feature = "otlp")]
+        if let Some(ref exporter) = self.exporter {
+            let attributes = [
+                opentelemetry::KeyValue::new("tool", tool.to_string()),
+                opentelemetry::KeyValue::new("outcome", outcome(ok)),
+            ];
+            exporter.tool_calls.add(1, &attributes);
+            exporter
+                .tool_duration
+                .record(elapsed.as_secs_f64(), &attributes);
+        }
+        #[cfg(not(feature = "otlp"))]
+        let _ = (tool, ok, elapsed);
+    }
+
+    /// Record budget reserved for a payment.
+    pub fn record_spend(&self, source: &'static str, amount: Amount) {
+        #[cfg(feature = "otlp")]
+        if let Some(ref exporter) = self.exporter {
+            exporter
+                .budget_spent
+                .add(amount, &[opentelemetry::KeyValue::new("source", source)]);
+        }
+        #[cfg(not(feature = "otlp"))]
+        let _ = (source, amount);
+    }
+
+    /// Record budget returned after a failed payment.
+    pub fn record_refund(&self, source: &'static str, amount: Amount) {
+        #[cfg(feature = "otlp")]
+        if let Some(ref exporter) = self.exporter {
+            exporter
+                .budget_refunded
+                .add(amount, &[opentelemetry::KeyValue::new("source", source)]);
+        }
+        #[cfg(not(feature = "otlp"))]
+        let _ = (source, amount);
+    }
+
+    /// Export pending metrics and stop. Does nothing without export.
+    pub fn shutdown(&self) {
+        #[cfg(feature = "otlp")]
+        if let Some(ref exporter) = self.exporter {
+            if let Err(e) = exporter.provider.shutdown() {
+                tracing::warn!(error = %e, "Failed to flush MCP metrics");
+            }
+        }
+    }
+}
+
+/// Value of the `outcome` attribute.
+#[cfg_attr(not(feature = "otlp"), allow(dead_code))]
+fn outcome(ok: bool) -> &'static str {
+    if ok {
+        "ok"
+    } else {
+        "error"
+    }
+}
+
+/// Meter provider exporting over OTLP, with its instruments.
+#[cfg(feature = "otlp")]
+struct Exporter {
+    provider: opentelemetry_sdk::metrics::SdkMeterProvider,
+    tool_calls: opentelemetry::metrics::Counter<u64>,
+    tool_duration: opentelemetry::metrics::Histogram<f64>,
+    budget_spent: opentelemetry::metrics::Counter<u64>,
+    budget_refunded: opentelemetry::metrics::Counter<u64>,
+}
+
+#[cfg(feature = "otlp")]
+impl Exporter {
+    fn new(config: &MetricsConfig, endpoint: &str) -> Result<Self, String> {
+        use opentelemetry::metrics::MeterProvider as _;
+        use opentelemetry_otlp::WithExportConfig;
+
+        let exporter = opentelemetry_otlp::MetricExporter::builder()
+            .with_http()
+            .with_endpoint(endpoint)
+            .build()
+            .map_err(|e| format!("invalid metrics endpoint: {}", e))?;
+        let reader = opentelemetry_sdk::metrics::PeriodicReader::builder(exporter)
+            .with_interval(Duration::from_secs(config.export_interval_secs.max(1)))
+            .build();
+        let resource = opentelemetry_sdk::Resource::builder()
+            .with_service_name(config.service_name.clone())
+            .build();
+        let provider = opentelemetry_sdk::metrics::SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_resource(resource)
+            .build();
+
+        let meter = provider.meter("nodalync-mcp");
+        Ok(Self {
+            tool_calls: meter
+                .u64_counter("nodalync.mcp.tool.calls")
+                .with_description("MCP tool invocations")
+                .build(),
+            tool_duration: meter
+                .f64_histogram("nodalync.mcp.tool.duration")
+                .with_description("MCP tool latency")
+                .with_unit("s")
+                .build(),
+            budget_spent: meter
+                .u64_counter("nodalync.mcp.budget.spent")
+                .with_description("Session budget reserved for payments")
+                .with_unit("tinybar")
+                .build(),
+            budget_refunded: meter
+                .u64_counter("nodalync.mcp.budget.refunded")
+                .with_description("Session budget returned after failed payments")
+                .with_unit("tinybar")
+                .build(),
+            provider,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_without_endpoint() {
+        let metrics = McpMetrics::init(&MetricsConfig::default()).unwrap();
+        assert!(!metrics.is_enabled());
+
+        // Recording without export is a no-op
+        metrics.record_tool_call("status", true, Duration::from_millis(5));
+        metrics.record_spend("tool", 100);
+        metrics.record_refund("tool", 100);
+        metrics.shutdown();
+    }
+
+    #[cfg(not(feature = "otlp"))]
+    #[test]
+    fn test_endpoint_needs_otlp_feature() {
+        let config = MetricsConfig {
+            endpoint: Some("http://localhost:4318/v1/metrics".to_string()),
+            ..Default::default()
+        };
+        assert!(McpMetrics::init(&config).is_err());
+    }
+
+    #[test]
+    fn test_outcome() {
+        assert_eq!(outcome(true), "ok");
+        assert_eq!(outcome(false), "error");
+    }
+}
@@ -9,43 +9,54 @@ use rmcp::{
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
     model::*,
     service::{RequestContext, RoleServer},
-    tool, tool_handler, tool_router, ErrorData as McpError,
+    tool, tool_router, ErrorData as McpError,
 };
-use tokio::sync::Mutex;
 use tracing::{debug, info, warn};
 
 use nodalync_crypto::{
-    content_hash, peer_id_from_public_key, PeerId as NodalyncPeerId, UNKNOWN_PEER_ID,
+    content_hash, peer_id_from_public_key, peer_id_from_string, Hash, PeerId as NodalyncPeerId,
+    UNKNOWN_PEER_ID,
 };
 use nodalync_net::{Multiaddr, Network, NetworkConfig, NetworkNode, PeerId as LibP2pPeerId};
-use nodalync_ops::DefaultNodeOperations;
+use nodalync_ops::{
+    AutoOpenPolicy, DefaultNodeOperations, ModerationConfig, OpsError, QueryResponse, TopUpConfig,
+    UsageReportConfig,
+};
 use nodalync_store::{
-    ChannelStore, ContentStore, ManifestFilter, ManifestStore, NodeState, NodeStateConfig,
+    ChannelStore, InvoiceDirection, InvoiceRecord, InvoiceStatus, ManifestFilter, ManifestStore,
+    NodeState, NodeStateConfig,
 };
-use nodalync_types::{ContentType, Visibility};
+use nodalync_types::{Amount, ContentType, Visibility};
+use nodalync_wire::SearchFilters;
 
 use crate::budget::{hbar_to_tinybars, tinybars_to_hbar, BudgetTracker};
 use crate::error::McpError as NodalyncMcpError;
+use crate::metrics::{McpMetrics, MetricsConfig};
 use crate::tools::{
     hash_to_string, string_to_hash, ChannelCloseResult, ChannelInfo, CloseAllChannelsOutput,
-    CloseChannelInput, ContentEarnings, DeleteContentInput, DeleteContentOutput, DepositHbarInput,
-    DepositHbarOutput, GetEarningsInput, GetEarningsOutput, ListSourcesInput, ListSourcesOutput,
-    ListVersionsInput, ListVersionsOutput, OpenChannelInput, OpenChannelOutput, PaymentDetails,
-    PreviewContentInput, PreviewContentOutput, PublishContentInput, PublishContentOutput,
-    QueryKnowledgeInput, QueryKnowledgeOutput, SearchNetworkInput, SearchNetworkOutput,
-    SearchResultInfo, SetVisibilityInput, SetVisibilityOutput, SourceInfo, StatusOutput,
-    SynthesizeContentInput, SynthesizeContentOutput, UpdateContentInput, UpdateContentOutput,
-    VersionEntry,
+    CloseChannelInput, ContentEarnings, CreateInvoiceInput, DeleteContentInput,
+    DeleteContentOutput, DepositHbarInput, DepositHbarOutput, GetEarningsInput, GetEarningsOutput,
+    InvoiceInfo, ListInvoicesInput, ListInvoicesOutput, ListRecommendedInput,
+    ListRecommendedOutput, ListSourcesInput, ListSourcesOutput, ListVersionsInput,
+    ListVersionsOutput, OpenChannelInput, OpenChannelOutput, PayInvoiceInput, PayInvoiceOutput,
+    PaymentDetails, PreviewContentInput, PreviewContentOutput, PublishContentInput,
+    PublishContentOutput, QueryKnowledgeInput, QueryKnowledgeOutput, RecommendationInfo,
+    ReportUsageInput, ReportUsageOutput, SearchNetworkInput, SearchNetworkOutput, SearchResultInfo,
+    SetVisibilityInput, SetVisibilityOutput, SourceInfo, StatusOutput, SynthesizeContentInput,
+    SynthesizeContentOutput, UpdateContentInput, UpdateContentOutput, VersionEntry,
 };
 
 /// Create a standardized error response for MCP tools.
 ///
-/// Returns a JSON-formatted error with error code, message, and recovery suggestion.
+/// Returns a JSON-formatted error with error code, severity, whether a
+/// retry may succeed, message, and recovery suggestion.
 fn tool_error(error: &NodalyncMcpError) -> CallToolResult {
     let code = error.error_code();
     let response = serde_json::json!({
         "error": code.to_string(),
         "code": code.code(),
+        "severity": code.severity(),
+        "retryable": code.is_retryable(),
         "message": error.to_string(),
         "suggestion": code.suggestion(),
     });
@@ -57,6 +68,35 @@ fn peer_id_to_string(peer_id: &NodalyncPeerId) -> String {
     bs58::encode(&peer_id.0).into_string()
 }
 
+/// Parse a Nodalync peer ID as produced by [`peer_id_to_string`].
+fn nodalync_peer_id_from_str(s: &str) -> Result<NodalyncPeerId, String> {
+    let bytes = bs58::decode(s)
+        .into_vec()
+        .map_err(|e| format!("invalid base58: {}", e))?;
+    let bytes: [u8; 20] = bytes
+        .try_into()
+        .map_err(|b: Vec<u8>| format!("invalid peer ID length: expected 20, got {}", b.len()))?;
+    Ok(NodalyncPeerId(bytes))
+}
+
+/// Convert a tracked invoice into its tool output form.
+fn invoice_info(record: &InvoiceRecord) -> InvoiceInfo {
+    let invoice = &record.invoice;
+    InvoiceInfo {
+        hash: hash_to_string(&invoice.hash),
+        payee: peer_id_to_string(&invoice.payee),
+        amount_hbar: tinybars_to_hbar(invoice.amount),
+        memo: invoice.memo.clone(),
+        reference: invoice.reference.as_ref().map(hash_to_string),
+        created_at: invoice.created_at,
+        expires_at: invoice.expires_at,
+        direction: record.direction.to_string(),
+        status: record.status.to_string(),
+        payer: record.payer.as_ref().map(peer_id_to_string),
+        paid_at: record.paid_at,
+    }
+}
+
 /// Configuration for the MCP server.
 #[derive(Debug, Clone)]
 pub struct McpServerConfig {
@@ -72,6 +112,19 @@ pub struct McpServerConfig {
     pub bootstrap_nodes: Vec<String>,
     /// Optional Hedera configuration for on-chain settlement.
     pub hedera: Option<HederaConfig>,
+    /// Limits on payment channels opened automatically while querying.
+    pub auto_open: AutoOpenPolicy,
+    /// Automatic settlement balance top-ups during the session.
+    pub top_up: TopUpConfig,
+    /// Usage reports sent for queried content and accepted for ours.
+    pub usage_reports: UsageReportConfig,
+    /// Moderation policy for content reports from other peers.
+    pub moderation: ModerationConfig,
+    /// OTLP export of tool call and budget metrics.
+    pub metrics: MetricsConfig,
+    /// API key of the tenant this session serves. Its queries are then
+    /// also charged to the tenant's budget and tagged with the tenant.
+    pub tenant_key: Option<String>,
 }
 
 /// Configuration for Hedera settlement integration.
@@ -106,6 +159,12 @@ impl Default for McpServerConfig {
                 .map(|s| s.to_string())
                 .collect(),
             hedera: None,
+            auto_open: AutoOpenPolicy::default(),
+            top_up: TopUpConfig::default(),
+            usage_reports: UsageReportConfig::default(),
+            moderation: ModerationConfig::default(),
+            metrics: MetricsConfig::default(),
+            tenant_key: None,
         }
     }
 }
@@ -116,8 +175,9 @@ impl Default for McpServerConfig {
 /// and `search_network` tools.
 #[derive(Clone)]
 pub struct NodalyncMcpServer {
-    /// Node operations instance.
-    ops: Arc<Mutex<DefaultNodeOperations>>,
+    /// Node operations, shared without a lock so tool calls run
+    /// concurrently.
+    ops: Arc<DefaultNodeOperations>,
     /// Budget tracker.
     budget: Arc<BudgetTracker>,
     /// Tool router for MCP.
@@ -128,6 +188,10 @@ pub struct NodalyncMcpServer {
     settlement: Option<Arc<dyn nodalync_settle::Settlement>>,
     /// Hedera configuration (if enabled).
     hedera_config: Option<HederaConfig>,
+    /// Tool call and budget metrics.
+    metrics: McpMetrics,
+    /// Tenant the session's queries are charged to.
+    tenant: Option<String>,
 }
 
 #[tool_router]
@@ -136,6 +200,9 @@ impl NodalyncMcpServer {
     pub async fn new(
         config: McpServerConfig,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        // Start metrics export first so a bad endpoint fails fast
+        let metrics = McpMetrics::init(&config.metrics)?;
+
         // Initialize node state
         let state_config = NodeStateConfig::new(&config.data_dir);
         let state = NodeState::open(state_config)?;
@@ -265,11 +332,26 @@ impl NodalyncMcpServer {
             (None, None) => DefaultNodeOperations::with_defaults(state, peer_id),
         };
 
+        ops.config.auto_open = config.auto_open.clone();
+        ops.config.top_up = config.top_up.clone();
+        ops.config.usage_reports = config.usage_reports.clone();
+        ops.config.moderation = config.moderation.clone();
+
         // Set the private key for signing payments
         ops.set_private_key(private_key);
 
-        // Wrap ops in Arc<Mutex> for sharing
-        let ops = Arc::new(Mutex::new(ops));
+        // Serve the session as a tenant if given its API key
+        let tenant = match config.tenant_key {
+            Some(ref key) => {
+                let tenant = ops.authenticate_tenant(key)?;
+                info!(tenant = %tenant.id, "Serving MCP session as tenant");
+                Some(tenant.id)
+            }
+            None => None,
+        };
+
+        // Share ops between tool calls and background tasks
+        let ops = Arc::new(ops);
 
         // Spawn background event processor if network is enabled
         if let Some(ref net) = network {
@@ -281,8 +363,7 @@ impl NodalyncMcpServer {
                 loop {
                     match network_clone.next_event().await {
                         Ok(event) => {
-                            let mut ops_guard = ops_clone.lock().await;
-                            if let Err(e) = ops_guard.handle_network_event(event).await {
+                            if let Err(e) = ops_clone.handle_network_event(event).await {
                                 warn!("MCP event handler error: {}", e);
                             }
                         }
@@ -305,14 +386,12 @@ impl NodalyncMcpServer {
                 loop {
                     tokio::time::sleep(Duration::from_secs(CLEANUP_INTERVAL_SECONDS)).await;
 
-                    let ops_guard = ops_cleanup.lock().await;
-                    let deleted = ops_guard
+                    let deleted = ops_cleanup
                         .state
                         .cleanup_old_announcements(ANNOUNCEMENT_TTL_SECONDS);
                     if deleted > 0 {
                         info!(deleted = deleted, "Cleaned up old announcements");
                     }
-                    drop(ops_guard);
                 }
             });
 
@@ -325,17 +404,19 @@ impl NodalyncMcpServer {
                 loop {
                     tokio::time::sleep(Duration::from_secs(SETTLEMENT_INTERVAL_SECONDS)).await;
 
-                    let mut ops_guard = ops_settlement.lock().await;
-
                     // Check if there are any channels that need settlement
-                    let channels = ops_guard.state.channels.list_open().unwrap_or_default();
+                    let channels = ops_settlement
+                        .state
+                        .channels
+                        .list_open()
+                        .unwrap_or_default();
                     if channels.is_empty() {
                         continue;
                     }
 
                     // Trigger settlement batch - this settles channels that have
                     // exceeded the threshold (100 HBAR) or time limit (1 hour)
-                    match ops_guard.trigger_settlement_batch().await {
+                    match ops_settlement.trigger_settlement_batch().await {
                         Ok(Some(batch_id)) => {
                             info!(
                                 batch_id = %batch_id,
@@ -350,8 +431,23 @@ impl NodalyncMcpServer {
                             warn!(error = %e, "Background settlement batch failed");
                         }
                     }
+                }
+            });
+        }
 
-                    drop(ops_guard);
+        // Spawn background top-up task so queries don't fail when the
+        // settlement balance runs dry mid-session
+        if config.top_up.enabled && settlement.is_some() {
+            let ops_top_up = Arc::clone(&ops);
+            let check_interval = Duration::from_secs(config.top_up.check_interval_secs.max(1));
+            tokio::spawn(async move {
+                loop {
+                    // Deposits and cap hits are logged by ops
+                    if let Err(e) = ops_top_up.check_top_up().await {
+                        warn!(error = %e, "Settlement top-up failed");
+                    }
+
+                    tokio::time::sleep(check_interval).await;
                 }
             });
         }
@@ -374,6 +470,8 @@ impl NodalyncMcpServer {
             network,
             settlement,
             hedera_config: config.hedera.clone(),
+            metrics,
+            tenant,
         })
     }
 
@@ -382,6 +480,31 @@ impl NodalyncMcpServer {
         Self::new(McpServerConfig::default()).await
     }
 
+    /// Reserve budget for a payment, recording it against `source`.
+    fn spend(&self, amount: Amount, source: &'static str) -> Option<Amount> {
+        let remaining = self.budget.spend(amount)?;
+        self.metrics.record_spend(source, amount);
+        Some(remaining)
+    }
+
+    /// Query content, charged to the session's tenant if it has one.
+    async fn query(&self, hash: &Hash, price: Amount) -> Result<QueryResponse, OpsError> {
+        match self.tenant {
+            Some(ref tenant) => {
+                self.ops
+                    .query_content_for_tenant(tenant, hash, price, None)
+                    .await
+            }
+            None => self.ops.query_content(hash, price, None).await,
+        }
+    }
+
+    /// Return budget reserved for a payment that failed.
+    fn refund(&self, amount: Amount, source: &'static str) {
+        self.budget.refund(amount);
+        self.metrics.record_refund(source, amount);
+    }
+
     /// Gracefully shutdown the MCP server, closing all payment channels.
     ///
     /// This should be called before dropping the server to ensure all payment
@@ -392,89 +515,95 @@ impl NodalyncMcpServer {
     pub async fn shutdown(&self) -> u32 {
         info!("MCP server shutting down, closing all payment channels...");
 
-        // Get list of open channels and private key
-        let (channels, private_key) = {
-            let ops = self.ops.lock().await;
-            let channels = ops.state.channels.list_open().unwrap_or_default();
-            let private_key = ops.private_key().cloned();
-            (channels, private_key)
-        };
-
-        if channels.is_empty() {
-            info!("No open payment channels to close");
-            return 0;
-        }
+        let ops = &self.ops;
 
-        let Some(private_key) = private_key else {
+        let Some(private_key) = ops.private_key().cloned() else {
             warn!("Private key not available, cannot close channels");
             return 0;
         };
 
-        let channels_count = channels.len() as u32;
-        info!(
-            channels_count = channels_count,
-            "Closing payment channels on shutdown"
-        );
-
-        let mut closed = 0u32;
-        let mut disputed = 0u32;
-        let mut failed = 0u32;
+        // Log progress from the ops event bus while the batch runs
+        let mut events = ops.subscribe_events();
+        let progress = tokio::spawn(async move {
+            while let Ok(event) = events.recv().await {
+                match event {
+                    nodalync_ops::OpsEvent::ChannelCloseStarted { total } if total > 0 => {
+                        info!(
+                            channels_count = total,
+                            "Closing payment channels on shutdown"
+                        );
+                    }
+                    nodalync_ops::OpsEvent::ChannelCloseProgress {
+                        peer,
+                        status,
+                        completed,
+                        total,
+                    } => {
+                        debug!(
+                            peer_id = %peer_id_to_string(&peer),
+                            status = ?status,
+                            completed = completed,
+                            total = total,
+                            "Channel close progress"
+                        );
+                    }
+                    nodalync_ops::OpsEvent::SettlementBatch { batch_id } => {
+                        info!(batch_id = %hash_to_string(&batch_id), "Shutdown settlement batch");
+                    }
+                    nodalync_ops::OpsEvent::ChannelCloseFinished { .. } => break,
+                    _ => {}
+                }
+            }
+        });
 
-        for (peer_id, _channel) in channels {
-            let peer_id_str = peer_id_to_string(&peer_id);
+        let report = match ops.close_all_payment_channels(&private_key).await {
+            Ok(report) => report,
+            Err(e) => {
+                warn!(error = %e, "Failed to close payment channels on shutdown");
+                progress.abort();
+                return 0;
+            }
+        };
+        let _ = progress.await;
 
-            // Try cooperative close with short timeout
-            let close_result = {
-                let mut ops = self.ops.lock().await;
-                tokio::time::timeout(
-                    Duration::from_secs(3),
-                    ops.close_payment_channel(&peer_id, &private_key),
-                )
-                .await
-            };
+        if report.total() == 0 {
+            info!("No open payment channels to close");
+            return 0;
+        }
 
-            match close_result {
-                Ok(Ok(nodalync_ops::CloseResult::Success { .. }))
-                | Ok(Ok(nodalync_ops::CloseResult::SuccessOffChain { .. })) => {
-                    closed += 1;
-                    debug!(peer_id = %peer_id_str, "Channel closed on shutdown");
+        // Peers that didn't cooperate get a dispute instead
+        let mut disputed = 0u32;
+        let mut failed = 0u32;
+        let uncooperative = report
+            .unresponsive
+            .iter()
+            .chain(report.failed.iter().map(|(peer, _)| peer));
+        for peer_id in uncooperative {
+            let peer_id_str = peer_id_to_string(peer_id);
+            match ops.dispute_payment_channel(peer_id, &private_key).await {
+                Ok(_tx_id) => {
+                    disputed += 1;
+                    debug!(peer_id = %peer_id_str, "Dispute initiated on shutdown");
                 }
-                Ok(Ok(nodalync_ops::CloseResult::PeerUnresponsive { .. }))
-                | Ok(Ok(nodalync_ops::CloseResult::OnChainFailed { .. }))
-                | Ok(Err(_))
-                | Err(_) => {
-                    // Peer unresponsive or error - initiate dispute
-                    let dispute_result = {
-                        let mut ops = self.ops.lock().await;
-                        ops.dispute_payment_channel(&peer_id, &private_key).await
-                    };
-
-                    match dispute_result {
-                        Ok(_tx_id) => {
-                            disputed += 1;
-                            debug!(peer_id = %peer_id_str, "Dispute initiated on shutdown");
-                        }
-                        Err(e) => {
-                            failed += 1;
-                            warn!(
-                                peer_id = %peer_id_str,
-                                error = %e,
-                                "Failed to close or dispute channel on shutdown"
-                            );
-                        }
-                    }
+                Err(e) => {
+                    failed += 1;
+                    warn!(
+                        peer_id = %peer_id_str,
+                        error = %e,
+                        "Failed to close or dispute channel on shutdown"
+                    );
                 }
             }
         }
 
         info!(
-            closed = closed,
+            closed = report.closed.len(),
             disputed = disputed,
             failed = failed,
             "Shutdown channel cleanup complete"
         );
 
-        channels_count
+        report.total() as u32
     }
 
     /// Query knowledge from the Nodalync network.
@@ -513,7 +642,7 @@ impl NodalyncMcpServer {
         };
 
         // Get preview to check price and find provider
-        let mut ops = self.ops.lock().await;
+        let ops = &self.ops;
         let preview = match ops.preview_content(&hash).await {
             Ok(p) => p,
             Err(e) => {
@@ -522,7 +651,7 @@ impl NodalyncMcpServer {
             }
         };
 
-        let price = preview.manifest.economics.price;
+        let price = ops.quote_price(&preview).await;
         let price_hbar = tinybars_to_hbar(price);
 
         // Check per-query budget limit
@@ -604,7 +733,7 @@ impl NodalyncMcpServer {
                     );
 
                     match ops
-                        .open_payment_channel_to_libp2p(libp2p_peer, channel_deposit)
+                        .auto_open_payment_channel_to_libp2p(libp2p_peer, channel_deposit)
                         .await
                     {
                         Ok((channel, remote_nodalync_id)) => {
@@ -623,6 +752,9 @@ impl NodalyncMcpServer {
                                 payment_details.channel_tx_id = Some(tx_id);
                             }
                         }
+                        Err(e @ nodalync_ops::OpsError::AutoOpenDenied(_)) => {
+                            return Ok(tool_error(&NodalyncMcpError::Ops(e)));
+                        }
                         Err(e) => {
                             warn!(error = %e, "Failed to open payment channel");
                         }
@@ -639,7 +771,7 @@ impl NodalyncMcpServer {
                 if !ops.has_open_channel(&peer).unwrap_or(false) {
                     let channel_deposit = hbar_to_tinybars(1.0);
 
-                    match ops.open_payment_channel(&peer, channel_deposit).await {
+                    match ops.auto_open_payment_channel(&peer, channel_deposit).await {
                         Ok(channel) => {
                             payment_details.channel_opened = true;
                             payment_details.channel_id = Some(hash_to_string(&channel.channel_id));
@@ -648,6 +780,9 @@ impl NodalyncMcpServer {
                             }
                             tokio::time::sleep(Duration::from_millis(500)).await;
                         }
+                        Err(e @ nodalync_ops::OpsError::AutoOpenDenied(_)) => {
+                            return Ok(tool_error(&NodalyncMcpError::Ops(e)));
+                        }
                         Err(e) => {
                             warn!(error = %e, "Failed to open payment channel");
                         }
@@ -657,7 +792,7 @@ impl NodalyncMcpServer {
         }
 
         // === RESERVE BUDGET AND EXECUTE QUERY ===
-        if price > 0 && self.budget.spend(price).is_none() {
+        if price > 0 && self.spend(price, "tool").is_none() {
             return Ok(tool_error(&NodalyncMcpError::BudgetExceeded {
                 cost: price,
                 remaining: self.budget.remaining(),
@@ -665,7 +800,7 @@ impl NodalyncMcpServer {
         }
 
         // Execute query with automatic retry on channel requirement
-        let response = match ops.query_content(&hash, price, None).await {
+        let response = match self.query(&hash, price).await {
             Ok(r) => r,
             Err(nodalync_ops::OpsError::ChannelRequiredWithPeerInfo {
                 nodalync_peer_id,
@@ -678,7 +813,7 @@ impl NodalyncMcpServer {
                 if let Some(ref libp2p_str) = libp2p_peer_id {
                     if let Ok(libp2p_peer) = libp2p_str.parse::<LibP2pPeerId>() {
                         if let Ok((channel, remote_id)) = ops
-                            .open_payment_channel_to_libp2p(libp2p_peer, channel_deposit)
+                            .auto_open_payment_channel_to_libp2p(libp2p_peer, channel_deposit)
                             .await
                         {
                             payment_details.channel_opened = true;
@@ -690,8 +825,9 @@ impl NodalyncMcpServer {
                         }
                     }
                 } else if let Some(ref nodalync_id) = nodalync_peer_id {
-                    if let Ok(channel) =
-                        ops.open_payment_channel(nodalync_id, channel_deposit).await
+                    if let Ok(channel) = ops
+                        .auto_open_payment_channel(nodalync_id, channel_deposit)
+                        .await
                     {
                         payment_details.channel_opened = true;
                         payment_details.channel_id = Some(hash_to_string(&channel.channel_id));
@@ -704,11 +840,11 @@ impl NodalyncMcpServer {
                 }
 
                 // Retry query
-                match ops.query_content(&hash, price, None).await {
+                match self.query(&hash, price).await {
                     Ok(r) => r,
                     Err(e) => {
                         if price > 0 {
-                            self.budget.refund(price);
+                            self.refund(price, "tool");
                         }
                         return Ok(tool_error(&NodalyncMcpError::Ops(e)));
                     }
@@ -716,7 +852,7 @@ impl NodalyncMcpServer {
             }
             Err(e) => {
                 if price > 0 {
-                    self.budget.refund(price);
+                    self.refund(price, "tool");
                 }
                 return Ok(tool_error(&NodalyncMcpError::Ops(e)));
             }
@@ -795,7 +931,7 @@ impl NodalyncMcpServer {
 
         let limit = input.limit.unwrap_or(10).min(50);
         let include_network = input.include_network.unwrap_or(false);
-        let mut ops = self.ops.lock().await;
+        let ops = &self.ops;
 
         let mut sources: Vec<SourceInfo> = Vec::new();
         let mut seen_hashes = std::collections::HashSet::new();
@@ -928,12 +1064,56 @@ impl NodalyncMcpServer {
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
+    /// List content recommended for the user.
+    ///
+    /// Ranks received network announcements against the user's L2 entity
+    /// graphs and query history.
+    #[tool(
+        description = "List network content recommended for you, based on your knowledge graph (L2) and the content you have queried before. Returns hashes, titles, prices, a relevance score, and the interests that matched. Use query_knowledge with a hash to retrieve one."
+    )]
+    async fn list_recommended(
+        &self,
+        Parameters(input): Parameters<ListRecommendedInput>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(limit = ?input.limit, "Processing list_recommended request");
+
+        let limit = input.limit.unwrap_or(10).min(50);
+        let ops = &self.ops;
+
+        let recommendations = ops
+            .recommended_content(limit as usize)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?
+            .into_iter()
+            .map(|r| RecommendationInfo {
+                hash: hash_to_string(&r.hash),
+                title: r.title,
+                content_type: format!("{:?}", r.content_type),
+                price_hbar: tinybars_to_hbar(r.price),
+                score: r.score,
+                matched_terms: r.matched_terms,
+                peer_id: r.publisher_peer_id,
+            })
+            .collect();
+
+        let output = ListRecommendedOutput { recommendations };
+
+        info!(
+            count = output.recommendations.len(),
+            "Listed recommendations"
+        );
+
+        let json = serde_json::to_string_pretty(&output)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
     /// Search the Nodalync network for knowledge sources.
     ///
     /// Step 1 of the knowledge query workflow: Find content by searching.
     /// Step 2: Use query_knowledge with the hash from search results.
     #[tool(
-        description = "Search the Nodalync network for knowledge. Returns a list of available content with hashes, titles, prices, and previews. Use the 'hash' field from results to query content with query_knowledge. Supports filtering by content_type (L0=raw documents, L3=synthesized insights)."
+        description = "Search the Nodalync network for knowledge. Returns a list of available content with hashes, titles, prices, and previews. Use the 'hash' field from results to query content with query_knowledge. Supports filtering by content_type (L0=raw documents, L3=synthesized insights), price range, tags, owner and publish time."
     )]
     async fn search_network(
         &self,
@@ -944,13 +1124,28 @@ impl NodalyncMcpServer {
 
         debug!(query = %input.query, limit = limit, "Processing search_network request");
 
-        // Parse content type filter
+        // Parse filters
         let content_type = input
             .content_type
             .as_ref()
             .and_then(|s| parse_content_type(s));
+        let owner = input
+            .owner
+            .as_deref()
+            .map(peer_id_from_string)
+            .transpose()
+            .map_err(|e| McpError::invalid_params(format!("Invalid owner: {}", e), None))?;
+        let filters = SearchFilters {
+            content_types: content_type.map(|ct| vec![ct]),
+            min_price: input.min_price_hbar.map(hbar_to_tinybars),
+            max_price: input.max_price_hbar.map(hbar_to_tinybars),
+            tags: input.tags.clone().filter(|tags| !tags.is_empty()),
+            owner,
+            created_after: input.published_after,
+            ..Default::default()
+        };
 
-        let mut ops = self.ops.lock().await;
+        let ops = &self.ops;
 
         // Check if network is available for live search
         let has_network = ops.has_network();
@@ -960,7 +1155,7 @@ impl NodalyncMcpServer {
 
         // Call search_network (searches local + cached + peers if network available)
         let results = ops
-            .search_network(&input.query, content_type, limit)
+            .search_network_filtered(&input.query, &filters, limit)
             .await
             .map_err(|e| McpError::internal_error(e.to_string(), None))?;
 
@@ -994,6 +1189,8 @@ impl NodalyncMcpServer {
                         },
                         source: r.source.to_string(),
                         peer_id: r.publisher_peer_id.clone(),
+                        latency_ms: r.latency_ms,
+                        verified_publisher: r.verified_publisher,
                         preview,
                         topics: r.l1_summary.primary_topics.clone(),
                     }
@@ -1026,18 +1223,14 @@ impl NodalyncMcpServer {
         description = "Get comprehensive status of the Nodalync node including network connectivity, session budget, payment channels, and Hedera balance. Use this as the primary status check."
     )]
     async fn status(&self) -> Result<CallToolResult, McpError> {
-        // Collect data from ops while holding lock, then release before async calls
-        let (peer_id, local_content_count, open_channels, channel_balance_tinybars, channels_info) = {
-            let ops = self.ops.lock().await;
+        // Collect data from ops before the async calls
+        let (peer_id, stats, open_channels, channel_balance_tinybars, channels_info) = {
+            let ops = &self.ops;
 
             let peer_id = ops.peer_id().to_string();
 
-            // Local content count
-            let filter = ManifestFilter::new();
-            let local_content_count = match ops.state.manifests.list(filter) {
-                Ok(manifests) => manifests.len() as u32,
-                Err(_) => 0,
-            };
+            // Content, storage, settlement and earnings
+            let stats = ops.stats().ok();
 
             // Channel status with detailed info
             let channels = ops.state.channels.list_open().unwrap_or_default();
@@ -1075,14 +1268,14 @@ impl NodalyncMcpServer {
 
             (
                 peer_id,
-                local_content_count,
+                stats,
                 open_channels,
                 channel_balance_tinybars,
                 channels_info,
             )
-        }; // ops lock released here
+        };
 
-        // Network status (no lock needed - network methods are thread-safe)
+        // Network status (network methods are thread-safe)
         let (connected_peers, is_bootstrapped) = if let Some(ref network) = self.network {
             let peers = network.connected_peers().len() as u32;
             (peers, peers > 0)
@@ -1090,7 +1283,7 @@ impl NodalyncMcpServer {
             (0, false)
         };
 
-        // Hedera status - async calls done without holding ops lock
+        // Hedera status
         // Fetch both account balance (on-chain HBAR) and contract balance (deposited funds)
         let (
             hedera_account_id,
@@ -1116,11 +1309,26 @@ impl NodalyncMcpServer {
             connected_peers,
             is_bootstrapped,
             peer_id,
-            local_content_count,
+            local_content_count: stats.as_ref().map_or(0, |s| s.content.total as u32),
+            // Node
+            uptime_secs: stats.as_ref().map_or(0, |s| s.uptime_secs),
+            storage_bytes: stats.as_ref().map_or(0, |s| s.storage_bytes()),
+            cache_hit_rate: stats.as_ref().map_or(0.0, |s| s.cache_hit_rate()),
+            pending_settlement_hbar: tinybars_to_hbar(
+                stats.as_ref().map_or(0, |s| s.pending_settlement_total),
+            ),
+            total_earned_hbar: tinybars_to_hbar(stats.as_ref().map_or(0, |s| s.total_earned)),
             // Budget
             budget_remaining_hbar: self.budget.remaining_hbar(),
             budget_total_hbar: self.budget.total_budget_hbar(),
             budget_spent_hbar: self.budget.spent_hbar(),
+            tenant: self.tenant.clone(),
+            tenant_budget_remaining_hbar: self
+                .tenant
+                .as_deref()
+                .and_then(|id| self.ops.get_tenant(id).ok())
+                .and_then(|tenant| tenant.remaining_budget())
+                .map(tinybars_to_hbar),
             // Channels
             open_channels,
             channel_balance_hbar: tinybars_to_hbar(channel_balance_tinybars),
@@ -1219,7 +1427,7 @@ impl NodalyncMcpServer {
         }
 
         let deposit_tinybars = hbar_to_tinybars(input.deposit_hbar);
-        let mut ops = self.ops.lock().await;
+        let ops = &self.ops;
 
         // Check if network is available
         if !ops.has_network() {
@@ -1401,7 +1609,7 @@ impl NodalyncMcpServer {
             nodalync_crypto::PeerId(peer_arr)
         };
 
-        let mut ops = self.ops.lock().await;
+        let ops = &self.ops;
 
         // Get channel info before closing
         let channel_info =
@@ -1432,7 +1640,6 @@ impl NodalyncMcpServer {
 
         // Attempt cooperative close with proper signature
         let result = ops.close_payment_channel(&peer_id, &private_key).await;
-        drop(ops); // Release lock before async Hedera calls
 
         // Get updated Hedera account balance
         let hedera_balance = if let Some(ref settlement) = self.settlement {
@@ -1499,7 +1706,7 @@ impl NodalyncMcpServer {
                     "Peer unresponsive, initiating dispute"
                 );
 
-                let mut ops = self.ops.lock().await;
+                let ops = &self.ops;
                 match ops.dispute_payment_channel(&peer_id, &private_key).await {
                     Ok(tx_id) => {
                         let output = CloseChannelOutput {
@@ -1564,7 +1771,7 @@ impl NodalyncMcpServer {
 
         // Get list of open channels and private key
         let (channels, private_key) = {
-            let ops = self.ops.lock().await;
+            let ops = &self.ops;
             let channels = ops.state.channels.list_open().unwrap_or_default();
             let private_key = ops.private_key().cloned();
             (channels, private_key)
@@ -1591,7 +1798,7 @@ impl NodalyncMcpServer {
 
             // Try cooperative close with timeout
             let close_result = {
-                let mut ops = self.ops.lock().await;
+                let ops = &self.ops;
                 tokio::time::timeout(
                     Duration::from_secs(5),
                     ops.close_payment_channel(&peer_id, &private_key),
@@ -1627,7 +1834,7 @@ impl NodalyncMcpServer {
                 | Err(_) => {
                     // Peer unresponsive or error - initiate dispute
                     let dispute_result = {
-                        let mut ops = self.ops.lock().await;
+                        let ops = &self.ops;
                         ops.dispute_payment_channel(&peer_id, &private_key).await
                     };
 
@@ -1649,7 +1856,7 @@ impl NodalyncMcpServer {
                         Err(e) => {
                             // If the channel was never funded on-chain, just remove it
                             if !was_funded_on_chain {
-                                let mut ops = self.ops.lock().await;
+                                let ops = &self.ops;
                                 let _ = ops.state.channels.delete(&peer_id);
                                 results.push(ChannelCloseResult {
                                     peer_id: peer_id_str.clone(),
@@ -1684,7 +1891,7 @@ impl NodalyncMcpServer {
                 Ok(Ok(nodalync_ops::CloseResult::OnChainFailed { error })) => {
                     // On-chain failed - try dispute
                     let dispute_result = {
-                        let mut ops = self.ops.lock().await;
+                        let ops = &self.ops;
                         ops.dispute_payment_channel(&peer_id, &private_key).await
                     };
 
@@ -1702,7 +1909,7 @@ impl NodalyncMcpServer {
                         Err(e) => {
                             // If the channel was never funded on-chain, just remove it
                             if !was_funded_on_chain {
-                                let mut ops = self.ops.lock().await;
+                                let ops = &self.ops;
                                 let _ = ops.state.channels.delete(&peer_id);
                                 results.push(ChannelCloseResult {
                                     peer_id: peer_id_str.clone(),
@@ -1808,7 +2015,7 @@ impl NodalyncMcpServer {
 
         // Check for duplicates
         let computed_hash = content_hash(content_bytes);
-        let mut ops = self.ops.lock().await;
+        let ops = &self.ops;
 
         if let Ok(Some(_)) = ops.get_content_manifest(&computed_hash) {
             return Ok(tool_error(&NodalyncMcpError::ContentAlreadyExists(
@@ -1886,7 +2093,7 @@ impl NodalyncMcpServer {
             Err(e) => return Ok(tool_error(&NodalyncMcpError::InvalidHash(e))),
         };
 
-        let mut ops = self.ops.lock().await;
+        let ops = &self.ops;
         let preview = match ops.preview_content(&hash).await {
             Ok(p) => p,
             Err(e) => return Ok(tool_error(&NodalyncMcpError::Ops(e))),
@@ -1920,6 +2127,7 @@ impl NodalyncMcpServer {
             topics: l1.primary_topics.clone(),
             summary: l1.summary.clone(),
             provider_peer_id: preview.provider_peer_id.clone(),
+            forks: preview.forks.iter().map(hash_to_string).collect(),
         };
 
         let json = serde_json::to_string_pretty(&output)
@@ -1979,7 +2187,7 @@ impl NodalyncMcpServer {
             metadata = metadata.with_description(desc);
         }
 
-        let mut ops = self.ops.lock().await;
+        let ops = &self.ops;
 
         // Derive content
         let hash = ops
@@ -2077,7 +2285,7 @@ impl NodalyncMcpServer {
             Err(e) => return Ok(tool_error(&NodalyncMcpError::InvalidHash(e))),
         };
 
-        let mut ops = self.ops.lock().await;
+        let ops = &self.ops;
 
         // Load old manifest for metadata inheritance
         let old_manifest = match ops.get_content_manifest(&old_hash) {
@@ -2152,10 +2360,11 @@ impl NodalyncMcpServer {
 
     /// Delete content and set visibility to Offline.
     ///
-    /// Removes content bytes and marks the manifest as offline.
+    /// Removes content bytes, marks the manifest as offline and broadcasts a
+    /// tombstone.
     /// The manifest is preserved for provenance tracking.
     #[tool(
-        description = "Delete content from your node. Removes the content bytes and sets visibility to Offline. The manifest is preserved for provenance tracking. Only works on content you own."
+        description = "Delete content from your node. Removes the content bytes, sets visibility to Offline, and broadcasts a tombstone so peers drop their copies. The manifest is preserved for provenance tracking. Only works on content you own."
     )]
     async fn delete_content(
         &self,
@@ -2168,10 +2377,10 @@ impl NodalyncMcpServer {
             Err(e) => return Ok(tool_error(&NodalyncMcpError::InvalidHash(e))),
         };
 
-        let mut ops = self.ops.lock().await;
+        let ops = &self.ops;
 
         // Load manifest and verify ownership
-        let mut manifest = match ops.get_content_manifest(&hash) {
+        let manifest = match ops.get_content_manifest(&hash) {
             Ok(Some(m)) => m,
             Ok(None) => {
                 return Ok(tool_error(&NodalyncMcpError::NotFound(hash_to_string(
@@ -2190,18 +2399,10 @@ impl NodalyncMcpServer {
 
         let title = manifest.metadata.title.clone();
 
-        // Delete content bytes
-        ops.state
-            .content
-            .delete(&hash)
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-        // Update manifest visibility to Offline
-        manifest.visibility = Visibility::Offline;
-        ops.state
-            .manifests
-            .update(&manifest)
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        // Delete content bytes, take it Offline and withdraw it from peers
+        if let Err(e) = ops.delete_content(&hash).await {
+            return Ok(tool_error(&NodalyncMcpError::Ops(e)));
+        }
 
         let output = DeleteContentOutput {
             hash: hash_to_string(&hash),
@@ -2245,7 +2446,7 @@ impl NodalyncMcpServer {
             }
         };
 
-        let mut ops = self.ops.lock().await;
+        let ops = &self.ops;
 
         // Get previous visibility
         let manifest = match ops.get_content_manifest(&hash) {
@@ -2300,7 +2501,7 @@ impl NodalyncMcpServer {
             Err(e) => return Ok(tool_error(&NodalyncMcpError::InvalidHash(e))),
         };
 
-        let ops = self.ops.lock().await;
+        let ops = &self.ops;
 
         // Load manifest to find version root
         let manifest = match ops.get_content_manifest(&hash) {
@@ -2358,7 +2559,7 @@ impl NodalyncMcpServer {
         debug!(limit = ?input.limit, content_type = ?input.content_type, "Processing get_earnings request");
 
         let limit = input.limit.unwrap_or(20).min(100);
-        let ops = self.ops.lock().await;
+        let ops = &self.ops;
 
         let peer_id = ops.peer_id();
         let mut filter = ManifestFilter::new().with_owner(peer_id).limit(limit);
@@ -2422,12 +2623,211 @@ impl NodalyncMcpServer {
 
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
+
+    /// Create a signed invoice for another peer to pay.
+    #[tool(
+        description = "Create a signed invoice requesting a payment to you in HBAR. Share the returned invoice hash and your peer ID with the payer; they can pay it with pay_invoice."
+    )]
+    async fn create_invoice(
+        &self,
+        Parameters(input): Parameters<CreateInvoiceInput>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(
+            amount_hbar = input.amount_hbar,
+            "Processing create_invoice request"
+        );
+
+        let amount = hbar_to_tinybars(input.amount_hbar);
+        if amount == 0 {
+            return Ok(tool_error(&NodalyncMcpError::Internal(
+                "Invoice amount must be greater than zero.".to_string(),
+            )));
+        }
+        let reference = match input.reference.as_deref().map(string_to_hash).transpose() {
+            Ok(reference) => reference,
+            Err(e) => return Ok(tool_error(&NodalyncMcpError::InvalidHash(e))),
+        };
+
+        let ops = &self.ops;
+        let invoice = match ops.create_invoice(
+            amount,
+            input.memo.unwrap_or_default(),
+            reference,
+            input
+                .expires_in_hours
+                .map(|hours| hours.saturating_mul(3_600_000)),
+        ) {
+            Ok(invoice) => invoice,
+            Err(e) => return Ok(tool_error(&NodalyncMcpError::Ops(e))),
+        };
+        let record = InvoiceRecord::new(invoice, InvoiceDirection::Issued);
+
+        info!(hash = %record.invoice.hash, amount, "Invoice created");
+
+        let json = serde_json::to_string_pretty(&invoice_info(&record))
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    /// Pay an invoice through the payment channel with its payee.
+    ///
+    /// The invoice amount is charged against the session budget.
+    #[tool(
+        description = "Pay an invoice through your payment channel with its payee. Provide payee_peer_id to fetch the invoice from the payee if it has not been received yet. The amount is charged against the session budget."
+    )]
+    async fn pay_invoice(
+        &self,
+        Parameters(input): Parameters<PayInvoiceInput>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(hash = %input.hash, "Processing pay_invoice request");
+
+        let hash = match string_to_hash(&input.hash) {
+            Ok(hash) => hash,
+            Err(e) => return Ok(tool_error(&NodalyncMcpError::InvalidHash(e))),
+        };
+        let payee = match input
+            .payee_peer_id
+            .as_deref()
+            .map(nodalync_peer_id_from_str)
+            .transpose()
+        {
+            Ok(payee) => payee,
+            Err(e) => return Ok(tool_error(&NodalyncMcpError::InvalidHash(e))),
+        };
+
+        let ops = &self.ops;
+
+        let record = match ops.get_invoice(&hash) {
+            Ok(record) => record,
+            Err(e) => return Ok(tool_error(&NodalyncMcpError::Ops(e))),
+        };
+        let amount = match (record, payee) {
+            (Some(record), _) => record.invoice.amount,
+            (None, Some(payee)) => match ops.fetch_invoice(&payee, &hash).await {
+                Ok(invoice) => invoice.amount,
+                Err(e) => return Ok(tool_error(&NodalyncMcpError::Ops(e))),
+            },
+            (None, None) => {
+                return Ok(tool_error(&NodalyncMcpError::NotFound(format!(
+                    "invoice {} has not been received; provide payee_peer_id to fetch it",
+                    input.hash
+                ))));
+            }
+        };
+
+        // Reserve budget before paying, refund if the payment fails
+        if self.spend(amount, "invoice").is_none() {
+            return Ok(tool_error(&NodalyncMcpError::BudgetExceeded {
+                cost: amount,
+                remaining: self.budget.remaining(),
+            }));
+        }
+
+        let record = match ops.pay_invoice(&hash).await {
+            Ok(record) => record,
+            Err(e) => {
+                self.refund(amount, "invoice");
+                return Ok(tool_error(&NodalyncMcpError::Ops(e)));
+            }
+        };
+
+        info!(hash = %hash, amount, "Invoice paid");
+
+        let output = PayInvoiceOutput {
+            invoice: invoice_info(&record),
+            remaining_budget_hbar: self.budget.remaining_hbar(),
+        };
+
+        let json = serde_json::to_string_pretty(&output)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    /// List issued and received invoices.
+    #[tool(
+        description = "List invoices you have issued or received, newest first. Optionally filter by direction (issued or received) and only show unpaid invoices."
+    )]
+    async fn list_invoices(
+        &self,
+        Parameters(input): Parameters<ListInvoicesInput>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(direction = ?input.direction, "Processing list_invoices request");
+
+        let direction = match input.direction.as_deref() {
+            Some(s) => match InvoiceDirection::parse(s) {
+                Some(direction) => Some(direction),
+                None => {
+                    return Ok(tool_error(&NodalyncMcpError::Internal(format!(
+                        "Invalid direction '{}'. Use issued or received.",
+                        s
+                    ))));
+                }
+            },
+            None => None,
+        };
+        let status = input
+            .unpaid_only
+            .unwrap_or(false)
+            .then_some(InvoiceStatus::Unpaid);
+
+        let ops = &self.ops;
+        let invoices: Vec<InvoiceInfo> = match ops.list_invoices(direction, status) {
+            Ok(records) => records.iter().map(invoice_info).collect(),
+            Err(e) => return Ok(tool_error(&NodalyncMcpError::Ops(e))),
+        };
+
+        let output = ListInvoicesOutput {
+            total: invoices.len(),
+            invoices,
+        };
+
+        let json = serde_json::to_string_pretty(&output)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    /// Report how queried content was used back to its publisher.
+    #[tool(
+        description = "Report how content you queried was used: bytes read, your context size in tokens, and a 1-5 rating of how useful it was. The report is anonymized and helps publishers see demand. Only sent if usage reports are enabled."
+    )]
+    async fn report_usage(
+        &self,
+        Parameters(input): Parameters<ReportUsageInput>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(hash = %input.hash, "Processing report_usage request");
+
+        let hash = match string_to_hash(&input.hash) {
+            Ok(h) => h,
+            Err(e) => return Ok(tool_error(&NodalyncMcpError::InvalidHash(e))),
+        };
+
+        let ops = &self.ops;
+        if let Err(e) = ops
+            .report_usage(&hash, input.bytes_read, input.context_tokens, input.rating)
+            .await
+        {
+            return Ok(tool_error(&NodalyncMcpError::Ops(e)));
+        }
+
+        info!(hash = %hash, bytes_read = input.bytes_read, "Usage reported");
+
+        let output = ReportUsageOutput {
+            hash: input.hash,
+            accepted: true,
+        };
+        let json = serde_json::to_string_pretty(&output)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
 }
 
 /// Knowledge resource URI prefix.
 const KNOWLEDGE_URI_PREFIX: &str = "knowledge://";
 
-#[tool_handler]
 impl rmcp::ServerHandler for NodalyncMcpServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
@@ -2447,6 +2847,35 @@ impl rmcp::ServerHandler for NodalyncMcpServer {
         }
     }
 
+    /// Run a tool, recording its outcome and latency.
+    #[allow(clippy::manual_async_fn)]
+    fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<CallToolResult, McpError>> + Send + '_ {
+        async move {
+            let tool = request.name.clone();
+            let started = std::time::Instant::now();
+
+            let tcc = rmcp::handler::server::tool::ToolCallContext::new(self, request, context);
+            let result = self.tool_router.call(tcc).await;
+
+            let ok = matches!(&result, Ok(r) if r.is_error != Some(true));
+            self.metrics.record_tool_call(&tool, ok, started.elapsed());
+            result
+        }
+    }
+
+    #[allow(clippy::manual_async_fn)]
+    fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<ListToolsResult, McpError>> + Send + '_ {
+        async move { Ok(ListToolsResult::with_all_items(self.tool_router.list_all())) }
+    }
+
     /// List available resource templates.
     ///
     /// Exposes the `knowledge://{hash}` URI template for direct content access.
@@ -2504,17 +2933,17 @@ impl rmcp::ServerHandler for NodalyncMcpServer {
             })?;
 
             // Get content preview to check price
-            let mut ops = self.ops.lock().await;
+            let ops = &self.ops;
             let preview = ops
                 .preview_content(&hash)
                 .await
                 .map_err(|e| McpError::invalid_params(format!("Content not found: {}", e), None))?;
 
-            let price = preview.manifest.economics.price;
+            let price = ops.quote_price(&preview).await;
             let price_hbar = tinybars_to_hbar(price);
 
             // Reserve budget before query
-            if price > 0 && self.budget.spend(price).is_none() {
+            if price > 0 && self.spend(price, "resource").is_none() {
                 return Err(McpError::invalid_request(
                     format!(
                         "Insufficient budget: content costs {:.6} HBAR but only {:.6} HBAR remaining",
@@ -2526,12 +2955,12 @@ impl rmcp::ServerHandler for NodalyncMcpServer {
             }
 
             // Execute query
-            let response = match ops.query_content(&hash, price, None).await {
+            let response = match self.query(&hash, price).await {
                 Ok(r) => r,
                 Err(e) => {
                     // Refund on failure
                     if price > 0 {
-                        self.budget.refund(price);
+                        self.refund(price, "resource");
                     }
                     return Err(McpError::internal_error(
                         format!("Query failed: {}", e),
@@ -2574,6 +3003,7 @@ pub async fn run_server(
         Err(e) => {
             info!("MCP transport closed during setup: {}", e);
             server_clone.shutdown().await;
+            server_clone.metrics.shutdown();
             return Ok(());
         }
     };
@@ -2587,6 +3017,7 @@ pub async fn run_server(
     // Server is shutting down - close all payment channels
     info!("MCP server stopping, cleaning up...");
     server_clone.shutdown().await;
+    server_clone.metrics.shutdown();
 
     Ok(())
 }
@@ -2710,6 +3141,7 @@ fn parse_content_type(s: &str) -> Option<ContentType> {
         "L1" => Some(ContentType::L1),
         "L2" => Some(ContentType::L2),
         "L3" => Some(ContentType::L3),
+        "COLLECTION" => Some(ContentType::Collection),
         _ => None,
     }
 }
@@ -2738,6 +3170,12 @@ mod tests {
             enable_network: false,
             bootstrap_nodes: vec![],
             hedera: None,
+            auto_open: AutoOpenPolicy::default(),
+            top_up: TopUpConfig::default(),
+            usage_reports: UsageReportConfig::default(),
+            moderation: ModerationConfig::default(),
+            metrics: MetricsConfig::default(),
+            tenant_key: None,
         }
     }
 
@@ -2762,6 +3200,30 @@ mod tests {
         assert!(!result.is_error.unwrap_or(false));
     }
 
+    #[tokio::test]
+    async fn test_tenant_session() {
+        let temp_dir = TempDir::new().unwrap();
+        let key = {
+            let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+            let ops = DefaultNodeOperations::with_defaults(state, UNKNOWN_PEER_ID);
+            ops.create_tenant("acme", "Acme Corp", Some(hbar_to_tinybars(0.5)))
+                .unwrap()
+                .1
+        };
+
+        let mut config = test_config(&temp_dir);
+        config.tenant_key = Some("ndk_not-a-key".to_string());
+        assert!(NodalyncMcpServer::new(config.clone()).await.is_err());
+
+        config.tenant_key = Some(key);
+        let server = NodalyncMcpServer::new(config).await.unwrap();
+        let result = server.status().await.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        let status: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(status["tenant"], "acme");
+        assert_eq!(status["tenant_budget_remaining_hbar"], 0.5);
+    }
+
     #[tokio::test]
     async fn test_list_sources_empty() {
         let temp_dir = TempDir::new().unwrap();
@@ -2780,6 +3242,73 @@ mod tests {
         assert!(!result.is_error.unwrap_or(false));
     }
 
+    #[tokio::test]
+    async fn test_list_recommended_empty() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = test_config(&temp_dir);
+
+        let server = NodalyncMcpServer::new(config).await.unwrap();
+        let input = ListRecommendedInput { limit: None };
+
+        let result = server.list_recommended(Parameters(input)).await.unwrap();
+
+        // No graph or history yet: empty feed, not an error
+        assert!(!result.is_error.unwrap_or(false));
+    }
+
+    #[tokio::test]
+    async fn test_create_and_list_invoices() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = test_config(&temp_dir);
+
+        let server = NodalyncMcpServer::new(config).await.unwrap();
+        let input = CreateInvoiceInput {
+            amount_hbar: 0.5,
+            memo: Some("Research".to_string()),
+            reference: None,
+            expires_in_hours: None,
+        };
+        let result = server.create_invoice(Parameters(input)).await.unwrap();
+        assert!(!result.is_error.unwrap_or(false));
+
+        let input = ListInvoicesInput {
+            direction: Some("issued".to_string()),
+            unpaid_only: Some(true),
+        };
+        let result = server.list_invoices(Parameters(input)).await.unwrap();
+        assert!(!result.is_error.unwrap_or(false));
+        let text = &result.content[0].as_text().unwrap().text;
+        let json: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(json["total"], 1);
+        assert_eq!(json["invoices"][0]["amount_hbar"], 0.5);
+
+        // Unknown invoice without a payee to fetch it from
+        let input = PayInvoiceInput {
+            hash: hash_to_string(&content_hash(b"missing")),
+            payee_peer_id: None,
+        };
+        let result = server.pay_invoice(Parameters(input)).await.unwrap();
+        assert!(result.is_error.unwrap_or(false));
+    }
+
+    #[tokio::test]
+    async fn test_report_usage_requires_opt_in() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = test_config(&temp_dir);
+
+        let server = NodalyncMcpServer::new(config).await.unwrap();
+        let input = ReportUsageInput {
+            hash: hash_to_string(&content_hash(b"queried")),
+            bytes_read: 1024,
+            context_tokens: Some(200_000),
+            rating: Some(5),
+        };
+        let result = server.report_usage(Parameters(input)).await.unwrap();
+        assert!(result.is_error.unwrap_or(false));
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.contains("usage reports are disabled"));
+    }
+
     #[tokio::test]
     async fn test_search_network_without_network() {
         let temp_dir = TempDir::new().unwrap();
@@ -2790,6 +3319,11 @@ mod tests {
             query: "test".to_string(),
             limit: None,
             content_type: None,
+            min_price_hbar: None,
+            max_price_hbar: None,
+            tags: None,
+            owner: None,
+            published_after: None,
         };
 
         // Should succeed even without network (searches local only)
@@ -2797,6 +3331,18 @@ mod tests {
         assert!(!result.is_error.unwrap_or(false));
     }
 
+    #[tokio::test]
+    async fn test_search_network_invalid_owner() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = test_config(&temp_dir);
+
+        let server = NodalyncMcpServer::new(config).await.unwrap();
+        let input: SearchNetworkInput =
+            serde_json::from_str(r#"{"query": "test", "owner": "not-a-peer"}"#).unwrap();
+
+        assert!(server.search_network(Parameters(input)).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_status_without_network() {
         let temp_dir = TempDir::new().unwrap();
@@ -2824,6 +3370,10 @@ mod tests {
         assert_eq!(parse_content_type("l1"), Some(ContentType::L1));
         assert_eq!(parse_content_type("L2"), Some(ContentType::L2));
         assert_eq!(parse_content_type("l3"), Some(ContentType::L3));
+        assert_eq!(
+            parse_content_type("collection"),
+            Some(ContentType::Collection)
+        );
         assert_eq!(parse_content_type("invalid"), None);
     }
 
@@ -2875,6 +3425,8 @@ mod tests {
                 let json: serde_json::Value = serde_json::from_str(text).unwrap();
                 assert_eq!(json["error"], "NOT_FOUND");
                 assert_eq!(json["code"], 1);
+                assert_eq!(json["severity"], "warning");
+                assert_eq!(json["retryable"], false);
                 assert!(json["message"].as_str().unwrap().contains("not found"));
                 assert!(json["suggestion"].is_string());
             }
@@ -193,6 +193,18 @@ pub struct StatusOutput {
     /// Total content items available locally.
     pub local_content_count: u32,
 
+    // === Node Statistics ===
+    /// Seconds since the node's operations started.
+    pub uptime_secs: u64,
+    /// Bytes of content held locally, stored and cached.
+    pub storage_bytes: u64,
+    /// Fraction of manifest lookups answered from memory.
+    pub cache_hit_rate: f64,
+    /// Amount waiting in the settlement queue, in HBAR.
+    pub pending_settlement_hbar: f64,
+    /// Revenue recorded across our own content, in HBAR.
+    pub total_earned_hbar: f64,
+
     // === Budget Status ===
     /// Remaining budget for this session in HBAR.
     pub budget_remaining_hbar: f64,
@@ -200,6 +212,12 @@ pub struct StatusOutput {
     pub budget_total_hbar: f64,
     /// Amount spent in this session in HBAR.
     pub budget_spent_hbar: f64,
+    /// Tenant the session's queries are charged to (if any).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    /// Budget the tenant has left in HBAR (if it has one).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_budget_remaining_hbar: Option<f64>,
 
     // === Channel Status ===
     /// Number of open payment channels.
@@ -247,6 +265,27 @@ pub struct SearchNetworkInput {
     /// Filter by content type (L0, L1, L2, L3).
     #[serde(default)]
     pub content_type: Option<String>,
+
+    /// Minimum price per query in HBAR.
+    #[serde(default)]
+    pub min_price_hbar: Option<f64>,
+
+    /// Maximum price per query in HBAR.
+    #[serde(default)]
+    pub max_price_hbar: Option<f64>,
+
+    /// Only content with all of these tags (case-insensitive; a tag also
+    /// matches its child tags, e.g. "science" matches "science/biology").
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+
+    /// Only content from this owner (ndl1... peer ID).
+    #[serde(default)]
+    pub owner: Option<String>,
+
+    /// Only content published after this time (Unix milliseconds).
+    #[serde(default)]
+    pub published_after: Option<u64>,
 }
 
 /// Output from the `search_network` tool.
@@ -291,6 +330,14 @@ pub struct SearchResultInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub peer_id: Option<String>,
 
+    /// Response time of the peer whose offer was kept, for peer results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+
+    /// Whether the owner is verified: our own content, an announcement
+    /// signed by its publisher, or a peer result returned by its owner.
+    pub verified_publisher: bool,
+
     /// Preview of L1 mentions (extracted facts/entities).
     pub preview: Vec<String>,
 
@@ -447,7 +494,8 @@ pub struct PublishContentInput {
     #[serde(default)]
     pub mime_type: Option<String>,
 
-    /// Optional tags for content discovery.
+    /// Optional tags for content discovery. Tags are hierarchical
+    /// ("science/biology") and normalized to lowercase.
     #[serde(default)]
     pub tags: Option<Vec<String>>,
 }
@@ -514,6 +562,9 @@ pub struct PreviewContentOutput {
     /// Provider peer ID (libp2p), if known.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub provider_peer_id: Option<String>,
+    /// Other versions the owner published with the same version number.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub forks: Vec<String>,
 }
 
 // ============================================================================
@@ -759,6 +810,172 @@ pub struct GetEarningsOutput {
     pub content_count: u32,
 }
 
+// ============================================================================
+// list_recommended Tool
+// ============================================================================
+
+/// Input for the `list_recommended` tool.
+///
+/// Lists network content recommended from the user's knowledge graph and
+/// query history.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ListRecommendedInput {
+    /// Maximum number of recommendations to return (default: 10, max: 50).
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+/// A single recommended content item.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RecommendationInfo {
+    /// Content hash (base58 encoded).
+    pub hash: String,
+    /// Content title.
+    pub title: String,
+    /// Content type (L0, L1, L3).
+    pub content_type: String,
+    /// Price per query in HBAR.
+    pub price_hbar: f64,
+    /// Relevance score (higher is more relevant).
+    pub score: f64,
+    /// Interests from the knowledge graph or query history that matched.
+    pub matched_terms: Vec<String>,
+    /// libp2p peer ID of the publisher, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer_id: Option<String>,
+}
+
+/// Output from the `list_recommended` tool.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ListRecommendedOutput {
+    /// Recommendations, most relevant first.
+    pub recommendations: Vec<RecommendationInfo>,
+}
+
+// ============================================================================
+// Invoice Tools
+// ============================================================================
+
+/// Input for the `create_invoice` tool.
+///
+/// Creates a signed invoice asking another peer to pay us.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct CreateInvoiceInput {
+    /// Amount to request in HBAR.
+    pub amount_hbar: f64,
+    /// Optional memo describing what the invoice is for.
+    #[serde(default)]
+    pub memo: Option<String>,
+    /// Optional content hash the invoice refers to (base58 encoded).
+    #[serde(default)]
+    pub reference: Option<String>,
+    /// Hours until the invoice expires (default: 168, one week).
+    #[serde(default)]
+    pub expires_in_hours: Option<u64>,
+}
+
+/// Input for the `pay_invoice` tool.
+///
+/// Pays an invoice through the payment channel with its payee.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct PayInvoiceInput {
+    /// Invoice hash (base58 encoded).
+    pub hash: String,
+    /// Nodalync peer ID of the payee, used to fetch the invoice if it has
+    /// not been received yet.
+    #[serde(default)]
+    pub payee_peer_id: Option<String>,
+}
+
+/// Input for the `list_invoices` tool.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ListInvoicesInput {
+    /// Only list invoices in one direction ("issued" or "received").
+    #[serde(default)]
+    pub direction: Option<String>,
+    /// Only list unpaid invoices (default: false).
+    #[serde(default)]
+    pub unpaid_only: Option<bool>,
+}
+
+/// A tracked invoice.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct InvoiceInfo {
+    /// Invoice hash (base58 encoded).
+    pub hash: String,
+    /// Nodalync peer ID of the payee.
+    pub payee: String,
+    /// Amount requested in HBAR.
+    pub amount_hbar: f64,
+    /// Memo describing what the invoice is for.
+    pub memo: String,
+    /// Content hash the invoice refers to, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    /// When the invoice was created (Unix ms).
+    pub created_at: u64,
+    /// When the invoice expires (Unix ms).
+    pub expires_at: u64,
+    /// "issued" or "received".
+    pub direction: String,
+    /// "unpaid" or "paid".
+    pub status: String,
+    /// Nodalync peer ID of the payer, once paid.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payer: Option<String>,
+    /// When the invoice was paid (Unix ms).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paid_at: Option<u64>,
+}
+
+/// Output from the `pay_invoice` tool.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PayInvoiceOutput {
+    /// The paid invoice.
+    pub invoice: InvoiceInfo,
+    /// Remaining session budget in HBAR.
+    pub remaining_budget_hbar: f64,
+}
+
+/// Output from the `list_invoices` tool.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ListInvoicesOutput {
+    /// Invoices, newest first.
+    pub invoices: Vec<InvoiceInfo>,
+    /// Number of invoices listed.
+    pub total: usize,
+}
+
+// ============================================================================
+// Usage Report Tools
+// ============================================================================
+
+/// Input for the `report_usage` tool.
+///
+/// Tells the publisher of queried content how it was used.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ReportUsageInput {
+    /// Content hash of the queried content (base58 encoded).
+    pub hash: String,
+    /// Bytes of the content that were actually read.
+    pub bytes_read: u64,
+    /// Size of the model context the content was used in, in tokens.
+    #[serde(default)]
+    pub context_tokens: Option<u32>,
+    /// How useful the content was, from 1 (useless) to 5 (essential).
+    #[serde(default)]
+    pub rating: Option<u8>,
+}
+
+/// Output from the `report_usage` tool.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ReportUsageOutput {
+    /// Content hash the report was about.
+    pub hash: String,
+    /// Whether the publisher recorded the report.
+    pub accepted: bool,
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
@@ -815,6 +1032,16 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_report_usage_input_deserialization() {
+        let json = r#"{"hash": "abc", "bytes_read": 2048, "rating": 4}"#;
+        let input: ReportUsageInput = serde_json::from_str(json).unwrap();
+
+        assert_eq!(input.bytes_read, 2048);
+        assert_eq!(input.rating, Some(4));
+        assert!(input.context_tokens.is_none());
+    }
+
     #[test]
     fn test_query_input_deserialization() {
         let json = r#"{"query": "What is Nodalync?"}"#;
@@ -878,9 +1105,16 @@ mod tests {
             is_bootstrapped: true,
             peer_id: "ndl1TestPeerId".to_string(),
             local_content_count: 42,
+            uptime_secs: 3600,
+            storage_bytes: 1_048_576,
+            cache_hit_rate: 0.5,
+            pending_settlement_hbar: 0.1,
+            total_earned_hbar: 2.5,
             budget_remaining_hbar: 0.75,
             budget_total_hbar: 1.0,
             budget_spent_hbar: 0.25,
+            tenant: None,
+            tenant_budget_remaining_hbar: None,
             open_channels: 2,
             channel_balance_hbar: 200.0,
             channels: vec![ChannelInfo {
@@ -907,6 +1141,8 @@ mod tests {
         assert_eq!(json["is_bootstrapped"], true);
         assert_eq!(json["peer_id"], "ndl1TestPeerId");
         assert_eq!(json["local_content_count"], 42);
+        assert_eq!(json["storage_bytes"], 1_048_576);
+        assert_eq!(json["total_earned_hbar"], 2.5);
         assert_eq!(json["open_channels"], 2);
         assert_eq!(json["hedera_configured"], true);
         assert_eq!(json["hedera_account_id"], "0.0.7703962");
@@ -1028,6 +1264,8 @@ mod tests {
             owner: "ndl1Owner123".to_string(),
             source: "peer".to_string(),
             peer_id: Some("12D3KooWSearchPeer".to_string()),
+            latency_ms: Some(42),
+            verified_publisher: true,
             preview: vec!["AI agents pay for knowledge".to_string()],
             topics: vec!["economics".to_string(), "ai".to_string()],
         };
@@ -1042,6 +1280,8 @@ mod tests {
         assert_eq!(json["owner"], "ndl1Owner123");
         assert_eq!(json["source"], "peer");
         assert_eq!(json["peer_id"], "12D3KooWSearchPeer");
+        assert_eq!(json["latency_ms"], 42);
+        assert_eq!(json["verified_publisher"], true);
         assert_eq!(json["preview"].as_array().unwrap().len(), 1);
         assert_eq!(json["topics"].as_array().unwrap().len(), 2);
     }
@@ -1064,6 +1304,18 @@ mod tests {
         assert_eq!(input.content_type, Some("L2".to_string()));
     }
 
+    #[test]
+    fn test_search_network_input_with_price_tag_owner_filters() {
+        let json = r#"{"query": "protocol", "min_price_hbar": 0.5, "max_price_hbar": 2.0,
+            "tags": ["p2p"], "owner": "ndl1abc", "published_after": 1700000000000}"#;
+        let input: SearchNetworkInput = serde_json::from_str(json).unwrap();
+        assert_eq!(input.min_price_hbar, Some(0.5));
+        assert_eq!(input.max_price_hbar, Some(2.0));
+        assert_eq!(input.tags, Some(vec!["p2p".to_string()]));
+        assert_eq!(input.owner.as_deref(), Some("ndl1abc"));
+        assert_eq!(input.published_after, Some(1_700_000_000_000));
+    }
+
     // ====================================================================
     // Content Production Tool Tests
     // ====================================================================
@@ -1122,12 +1374,14 @@ mod tests {
             topics: vec!["topic1".to_string()],
             summary: "A test summary".to_string(),
             provider_peer_id: Some("12D3KooWProvider".to_string()),
+            forks: vec![],
         };
         let json_str = serde_json::to_string(&output).unwrap();
         let json: serde_json::Value = serde_json::from_str(&json_str).unwrap();
         assert_eq!(json["hash"], "QmTestHash");
         assert_eq!(json["mention_count"], 3);
         assert_eq!(json["provider_peer_id"], "12D3KooWProvider");
+        assert!(json.get("forks").is_none());
     }
 
     #[test]
@@ -1289,4 +1543,44 @@ mod tests {
         assert_eq!(json["content_count"], 1);
         assert_eq!(json["items"].as_array().unwrap().len(), 1);
     }
+
+    #[test]
+    fn test_invoice_inputs_deserialization() {
+        let input: CreateInvoiceInput = serde_json::from_str(r#"{"amount_hbar": 1.5}"#).unwrap();
+        assert_eq!(input.amount_hbar, 1.5);
+        assert!(input.memo.is_none());
+        assert!(input.expires_in_hours.is_none());
+
+        let input: PayInvoiceInput = serde_json::from_str(r#"{"hash": "QmInvoice"}"#).unwrap();
+        assert_eq!(input.hash, "QmInvoice");
+        assert!(input.payee_peer_id.is_none());
+
+        let input: ListInvoicesInput = serde_json::from_str(r#"{}"#).unwrap();
+        assert!(input.direction.is_none());
+        assert!(input.unpaid_only.is_none());
+    }
+
+    #[test]
+    fn test_invoice_info_serialization() {
+        let info = InvoiceInfo {
+            hash: "QmInvoice".to_string(),
+            payee: "ndlPayee".to_string(),
+            amount_hbar: 1.5,
+            memo: "Consulting".to_string(),
+            reference: None,
+            created_at: 1_000,
+            expires_at: 2_000,
+            direction: "received".to_string(),
+            status: "unpaid".to_string(),
+            payer: None,
+            paid_at: None,
+        };
+        let json_str = serde_json::to_string(&info).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(json["amount_hbar"], 1.5);
+        assert_eq!(json["status"], "unpaid");
+        // Optional fields omitted when not set
+        assert!(json.get("reference").is_none());
+        assert!(json.get("payer").is_none());
+    }
 }
@@ -0,0 +1,115 @@
+#ifndef NODALYNC_H
+#define NODALYNC_H
+
+/* Generated by cbindgen from crates/bindings/nodalync-ffi. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// An open embedded node.
+typedef struct NodalyncNode NodalyncNode;
+
+// Node configuration supplied by the host app.
+//
+// Optional paths may be NULL to use their defaults under `data_dir`.
+typedef struct NodalyncConfig {
+  // Base directory for node data (required).
+  const char *data_dir;
+  // Content directory (default: `data_dir/content`).
+  const char *content_dir;
+  // Directory queried content is saved to (default: `data_dir/cache`).
+  const char *cache_dir;
+  // Database file (default: `data_dir/nodalync.db`).
+  const char *database_path;
+  // Password protecting the identity (required).
+  const char *password;
+  // Connect to the network.
+  bool enable_network;
+  // Newline-separated bootstrap multiaddrs, or NULL for none.
+  const char *bootstrap_nodes;
+} NodalyncConfig;
+
+// Open a node, creating its identity on first use.
+//
+// Returns NULL on failure. Release the node with [`nodalync_node_free`].
+//
+// # Safety
+//
+// `config` must point to a valid [`NodalyncConfig`] whose non-NULL
+// strings are valid NUL-terminated strings.
+struct NodalyncNode *nodalync_node_open(const struct NodalyncConfig *config);
+
+// Close a node and release its handle. NULL is ignored.
+//
+// # Safety
+//
+// `node` must be NULL or a handle from [`nodalync_node_open`] that has not
+// been freed.
+void nodalync_node_free(struct NodalyncNode *node);
+
+// Our peer ID, in `ndl1...` form.
+//
+// # Safety
+//
+// `node` must be a valid handle.
+char *nodalync_node_peer_id(const struct NodalyncNode *node);
+
+// Create and publish content as shared, returning its hash.
+//
+// `price` is in tinybars.
+//
+// # Safety
+//
+// `node` must be a valid handle, `content` must point to `content_len`
+// readable bytes, and `title` must be a valid string.
+char *nodalync_node_publish(const struct NodalyncNode *node,
+                            const uint8_t *content,
+                            size_t content_len,
+                            const char *title,
+                            uint64_t price);
+
+// Preview content as a JSON object.
+//
+// # Safety
+//
+// `node` must be a valid handle and `hash` a valid string.
+char *nodalync_node_preview(const struct NodalyncNode *node, const char *hash);
+
+// Query (and pay for) content, returning the result as a JSON object.
+//
+// The content is written to `output_path`, or to the cache directory when
+// it is NULL.
+//
+// # Safety
+//
+// `node` must be a valid handle, `hash` a valid string, and `output_path`
+// NULL or a valid string.
+char *nodalync_node_query(const struct NodalyncNode *node,
+                          const char *hash,
+                          const char *output_path);
+
+// Open payment channels as a JSON array.
+//
+// # Safety
+//
+// `node` must be a valid handle.
+char *nodalync_node_channels(const struct NodalyncNode *node);
+
+// Release a string returned by this library. NULL is ignored.
+//
+// # Safety
+//
+// `s` must be NULL or a string returned by this library that has not been
+// freed.
+void nodalync_string_free(char *s);
+
+// The last error on this thread, or NULL if there was none.
+//
+// The string is owned by the library and valid until the next call on
+// this thread.
+const char *nodalync_last_error(void);
+
+#endif  /* NODALYNC_H */
@@ -0,0 +1,410 @@
+//! A consumer node embedded in a host app.
+//!
+//! The embedder owns its own Tokio runtime and exposes a small blocking API
+//! (identity, publish, preview, query, channel status) that the C layer
+//! wraps. Storage locations come from the host, since mobile apps must keep
+//! data inside their sandbox.
+//!
+//! The node is consumer-only: it runs no background settlement, scheduling
+//! or maintenance tasks, and only handles network events (announcements,
+//! channel messages) while it is open.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use nodalync_crypto::{peer_id_from_public_key, peer_id_to_string, Hash, PrivateKey};
+use nodalync_net::multiaddr::Protocol;
+use nodalync_net::{Multiaddr, Network, NetworkConfig, NetworkNode, PeerId as LibP2pPeerId};
+use nodalync_ops::DefaultNodeOperations;
+use nodalync_store::{ChannelStore, NodeState, NodeStateConfig};
+use nodalync_types::{Metadata, Visibility};
+use serde::Serialize;
+use tokio::runtime::Runtime;
+use tracing::{info, warn};
+
+use crate::error::{FfiError, FfiResult};
+
+/// How long to wait for bootstrap before continuing with fewer peers.
+const BOOTSTRAP_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Storage locations and network settings supplied by the host app.
+#[derive(Debug, Clone)]
+pub struct EmbedderConfig {
+    /// Base directory for node data.
+    pub data_dir: PathBuf,
+    /// Content directory (default: `data_dir/content`).
+    pub content_dir: Option<PathBuf>,
+    /// Cache directory, where queried content is saved by default
+    /// (default: `data_dir/cache`).
+    pub cache_dir: Option<PathBuf>,
+    /// Database file (default: `data_dir/nodalync.db`).
+    pub database_path: Option<PathBuf>,
+    /// Password protecting the identity; an identity is created on first open.
+    pub password: String,
+    /// Connect to the network (otherwise only local content is available).
+    pub enable_network: bool,
+    /// Bootstrap multiaddrs, each ending in `/p2p/<peer id>`.
+    pub bootstrap_nodes: Vec<String>,
+}
+
+impl EmbedderConfig {
+    /// Create a configuration with default paths under `data_dir`.
+    pub fn new(data_dir: impl Into<PathBuf>, password: impl Into<String>) -> Self {
+        Self {
+            data_dir: data_dir.into(),
+            content_dir: None,
+            cache_dir: None,
+            database_path: None,
+            password: password.into(),
+            enable_network: false,
+            bootstrap_nodes: Vec::new(),
+        }
+    }
+
+    /// Directory queried content is saved to by default.
+    pub fn cache_dir(&self) -> PathBuf {
+        self.cache_dir
+            .clone()
+            .unwrap_or_else(|| self.data_dir.join("cache"))
+    }
+}
+
+/// Content preview.
+#[derive(Debug, Clone, Serialize)]
+pub struct PreviewInfo {
+    pub hash: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub owner: String,
+    /// Price per query, in tinybars.
+    pub price: u64,
+    pub size: u64,
+    pub mention_count: u32,
+    /// Preview mention texts.
+    pub mentions: Vec<String>,
+    pub topics: Vec<String>,
+}
+
+/// Result of a query.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryInfo {
+    pub hash: String,
+    pub title: String,
+    /// Amount paid, in tinybars.
+    pub price_paid: u64,
+    /// Where the content was saved.
+    pub saved_to: String,
+}
+
+/// Status of an open payment channel.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelInfo {
+    pub channel_id: String,
+    pub peer_id: String,
+    pub state: String,
+    /// Our balance, in tinybars.
+    pub my_balance: u64,
+    /// The peer's balance, in tinybars.
+    pub their_balance: u64,
+    pub pending_payments: u32,
+}
+
+/// An embedded consumer node.
+pub struct Embedder {
+    config: EmbedderConfig,
+    runtime: Runtime,
+    ops: Arc<DefaultNodeOperations>,
+}
+
+impl Embedder {
+    /// Open the node, creating its identity on first use.
+    pub fn open(config: EmbedderConfig) -> FfiResult<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()?;
+
+        let mut state_config = NodeStateConfig::new(&config.data_dir);
+        state_config.content_dir = config.content_dir.clone();
+        state_config.cache_dir = config.cache_dir.clone();
+        state_config.database_path = config.database_path.clone();
+        let state = NodeState::open(state_config)?;
+
+        if !state.identity.exists() {
+            let peer_id = state.identity.generate(&config.password)?;
+            info!(peer_id = %peer_id, "Created identity");
+        }
+        let (private_key, public_key) = state.identity.load(&config.password)?;
+        let peer_id = peer_id_from_public_key(&public_key);
+
+        let mut ops = if config.enable_network {
+            let network = runtime.block_on(start_network(&config, &private_key, public_key))?;
+            DefaultNodeOperations::with_defaults_and_network(
+                state,
+                peer_id,
+                Arc::clone(&network) as Arc<dyn Network>,
+            )
+        } else {
+            DefaultNodeOperations::with_defaults(state, peer_id)
+        };
+        ops.set_private_key(private_key);
+        let ops = Arc::new(ops);
+
+        if let Some(network) = ops.network().cloned() {
+            let ops = Arc::clone(&ops);
+            runtime.spawn(async move {
+                loop {
+                    match network.next_event().await {
+                        Ok(event) => {
+                            if let Err(e) = ops.handle_network_event(event).await {
+                                warn!("Event handler error: {}", e);
+                            }
+                        }
+                        Err(e) => {
+                            warn!("Network event error: {} - stopping event processor", e);
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+
+        Ok(Self {
+            config,
+            runtime,
+            ops,
+        })
+    }
+
+    /// Our peer ID, in `ndl1...` form.
+    pub fn peer_id(&self) -> String {
+        peer_id_to_string(&self.ops.peer_id())
+    }
+
+    /// Create and publish content as shared, returning its hash.
+    pub fn publish(&self, content: &[u8], title: &str, price: u64) -> FfiResult<String> {
+        let metadata = Metadata::new(title, content.len() as u64);
+        let hash = self.ops.create_content(content, metadata)?;
+        self.runtime
+            .block_on(self.ops.publish_content(&hash, Visibility::Shared, price))?;
+        Ok(hash.to_string())
+    }
+
+    /// Preview content, looking it up on the network if it isn't local.
+    pub fn preview(&self, hash: &str) -> FfiResult<PreviewInfo> {
+        let hash = parse_hash(hash)?;
+        let preview = self
+            .runtime
+            .block_on(self.ops.preview_content(&hash))
+            .map_err(|_| FfiError::NotFound(hash.to_string()))?;
+
+        let manifest = preview.manifest;
+        let summary = preview.l1_summary;
+        Ok(PreviewInfo {
+            hash: manifest.hash.to_string(),
+            title: manifest.metadata.title,
+            description: manifest.metadata.description,
+            owner: peer_id_to_string(&manifest.owner),
+            price: manifest.economics.price,
+            size: manifest.metadata.content_size,
+            mention_count: summary.mention_count,
+            mentions: summary
+                .preview_mentions
+                .into_iter()
+                .map(|m| m.content)
+                .collect(),
+            topics: summary.primary_topics,
+        })
+    }
+
+    /// Query (and pay for) content, saving it to `output` or the cache
+    /// directory.
+    pub fn query(&self, hash: &str, output: Option<&Path>) -> FfiResult<QueryInfo> {
+        let hash = parse_hash(hash)?;
+        let response = self.runtime.block_on(async {
+            let manifest = match self.ops.get_content_manifest(&hash)? {
+                Some(manifest) => manifest,
+                None => {
+                    self.ops
+                        .preview_content(&hash)
+                        .await
+                        .map_err(|_| FfiError::NotFound(hash.to_string()))?
+                        .manifest
+                }
+            };
+            let price = manifest.economics.price;
+            let response = self.ops.query_content(&hash, price, None).await?;
+            Ok::<_, FfiError>((response, manifest.metadata.title, price))
+        });
+        let (response, title, price_paid) = response?;
+
+        let saved_to = match output {
+            Some(path) => path.to_path_buf(),
+            None => self.config.cache_dir().join(hash.to_string()),
+        };
+        if let Some(parent) = saved_to.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&saved_to, &response.content)?;
+
+        Ok(QueryInfo {
+            hash: hash.to_string(),
+            title,
+            price_paid,
+            saved_to: saved_to.display().to_string(),
+        })
+    }
+
+    /// Status of our open payment channels.
+    pub fn channels(&self) -> FfiResult<Vec<ChannelInfo>> {
+        Ok(self
+            .ops
+            .state
+            .channels
+            .list_open()?
+            .into_iter()
+            .map(|(peer_id, channel)| ChannelInfo {
+                channel_id: channel.channel_id.to_string(),
+                peer_id: peer_id_to_string(&peer_id),
+                state: format!("{:?}", channel.state),
+                my_balance: channel.my_balance,
+                their_balance: channel.their_balance,
+                pending_payments: channel.pending_payments.len() as u32,
+            })
+            .collect())
+    }
+}
+
+/// Create the network node, subscribe to announcements and bootstrap.
+async fn start_network(
+    config: &EmbedderConfig,
+    private_key: &PrivateKey,
+    public_key: nodalync_crypto::PublicKey,
+) -> FfiResult<Arc<NetworkNode>> {
+    let mut bootstrap_nodes = Vec::new();
+    for addr in &config.bootstrap_nodes {
+        bootstrap_nodes.push(parse_bootstrap_address(addr)?);
+    }
+
+    // Peers answer requests over our connections, so listen like any node
+    let net_config = NetworkConfig {
+        listen_addresses: vec!["/ip4/0.0.0.0/tcp/0".parse().expect("valid listen address")],
+        bootstrap_nodes,
+        ..Default::default()
+    };
+
+    let secret =
+        nodalync_net::identity::ed25519::SecretKey::try_from_bytes(private_key.as_bytes().to_vec())
+            .map_err(|e| FfiError::invalid(format!("invalid Ed25519 key: {}", e)))?;
+    let keypair = nodalync_net::identity::Keypair::from(
+        nodalync_net::identity::ed25519::Keypair::from(secret),
+    );
+
+    let node =
+        NetworkNode::with_keypair(private_key.clone(), public_key, keypair, net_config).await?;
+    if let Err(e) = node.subscribe_announcements().await {
+        warn!("Failed to subscribe to announcements: {}", e);
+    }
+
+    match tokio::time::timeout(BOOTSTRAP_TIMEOUT, node.bootstrap()).await {
+        Ok(Ok(())) => info!(peers = node.connected_peers().len(), "Bootstrap complete"),
+        Ok(Err(e)) => warn!(
+            "Bootstrap failed: {} - continuing with limited connectivity",
+            e
+        ),
+        Err(_) => warn!("Bootstrap timed out - continuing with limited connectivity"),
+    }
+
+    Ok(Arc::new(node))
+}
+
+/// Split a bootstrap multiaddr into its libp2p peer ID and dial address.
+fn parse_bootstrap_address(addr: &str) -> FfiResult<(LibP2pPeerId, Multiaddr)> {
+    let invalid = || FfiError::invalid(format!("invalid bootstrap address: {}", addr));
+    let addr: Multiaddr = addr.parse().map_err(|_| invalid())?;
+
+    let peer_id = addr
+        .iter()
+        .find_map(|proto| match proto {
+            Protocol::P2p(peer_id) => Some(peer_id),
+            _ => None,
+        })
+        .ok_or_else(invalid)?;
+    let dial_addr = addr
+        .iter()
+        .filter(|proto| !matches!(proto, Protocol::P2p(_)))
+        .collect();
+
+    Ok((peer_id, dial_addr))
+}
+
+/// Parse a hex content hash.
+fn parse_hash(hash: &str) -> FfiResult<Hash> {
+    serde_json::from_value(serde_json::Value::String(hash.to_string()))
+        .map_err(|_| FfiError::invalid(format!("invalid hash: {}", hash)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn open(temp_dir: &TempDir) -> Embedder {
+        let mut config = EmbedderConfig::new(temp_dir.path().join("node"), "password");
+        config.cache_dir = Some(temp_dir.path().join("app-cache"));
+        Embedder::open(config).unwrap()
+    }
+
+    #[test]
+    fn test_identity_persists() {
+        let temp_dir = TempDir::new().unwrap();
+        let peer_id = open(&temp_dir).peer_id();
+        assert!(peer_id.starts_with("ndl1"));
+        assert_eq!(open(&temp_dir).peer_id(), peer_id);
+
+        let mut config = EmbedderConfig::new(temp_dir.path().join("node"), "wrong");
+        config.cache_dir = Some(temp_dir.path().join("app-cache"));
+        assert!(Embedder::open(config).is_err());
+    }
+
+    #[test]
+    fn test_publish_preview_and_query() {
+        let temp_dir = TempDir::new().unwrap();
+        let node = open(&temp_dir);
+
+        let hash = node
+            .publish(b"Field notes from the embedded node", "Field notes", 0)
+            .unwrap();
+        let preview = node.preview(&hash).unwrap();
+        assert_eq!(preview.title, "Field notes");
+        assert_eq!(preview.owner, node.peer_id());
+
+        let result = node.query(&hash, None).unwrap();
+        assert_eq!(result.price_paid, 0);
+        assert!(result
+            .saved_to
+            .starts_with(&*temp_dir.path().join("app-cache").to_string_lossy()));
+        assert_eq!(
+            std::fs::read(&result.saved_to).unwrap(),
+            b"Field notes from the embedded node"
+        );
+
+        assert!(node.channels().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        let temp_dir = TempDir::new().unwrap();
+        let node = open(&temp_dir);
+        assert!(matches!(
+            node.preview("not-a-hash"),
+            Err(FfiError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            node.preview(&"0".repeat(64)),
+            Err(FfiError::NotFound(_))
+        ));
+        assert!(parse_bootstrap_address("/ip4/127.0.0.1/tcp/9000").is_err());
+    }
+}
@@ -0,0 +1,52 @@
+//! Error types for the embedder API.
+
+use nodalync_net::NetworkError;
+use nodalync_ops::OpsError;
+use nodalync_store::StoreError;
+use thiserror::Error;
+
+/// Result type for embedder operations.
+pub type FfiResult<T> = Result<T, FfiError>;
+
+/// Errors reported to the host app.
+#[derive(Debug, Error)]
+pub enum FfiError {
+    /// A null pointer, invalid UTF-8 or malformed argument.
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+
+    /// Content not found locally or on the network.
+    #[error("content not found: {0}")]
+    NotFound(String),
+
+    /// Storage error.
+    #[error("storage error: {0}")]
+    Store(#[from] StoreError),
+
+    /// Operations error.
+    #[error("{0}")]
+    Ops(#[from] OpsError),
+
+    /// Network error.
+    #[error("network error: {0}")]
+    Network(#[from] NetworkError),
+
+    /// I/O error.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// JSON serialization error.
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    /// A Rust panic was caught at the FFI boundary.
+    #[error("internal error: {0}")]
+    Panic(String),
+}
+
+impl FfiError {
+    /// Create an invalid argument error.
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Self::InvalidArgument(message.into())
+    }
+}
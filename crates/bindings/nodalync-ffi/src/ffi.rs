@@ -0,0 +1,353 @@
+//! C interface over [`Embedder`].
+//!
+//! Conventions:
+//! - Functions returning a pointer return NULL on failure; the reason is
+//!   available from [`nodalync_last_error`] on the same thread.
+//! - Strings passed in are NUL-terminated UTF-8 and borrowed for the call.
+//! - Strings returned are owned by the caller and must be released with
+//!   [`nodalync_string_free`]. Structured results are JSON objects.
+//! - A node handle may be used from any thread, but calls on one handle
+//!   must not overlap.
+//! - Panics are caught at the boundary and reported as errors.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::ptr;
+
+use serde::Serialize;
+
+use crate::embedder::{Embedder, EmbedderConfig};
+use crate::error::{FfiError, FfiResult};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Node configuration supplied by the host app.
+///
+/// Optional paths may be NULL to use their defaults under `data_dir`.
+#[repr(C)]
+pub struct NodalyncConfig {
+    /// Base directory for node data (required).
+    pub data_dir: *const c_char,
+    /// Content directory (default: `data_dir/content`).
+    pub content_dir: *const c_char,
+    /// Directory queried content is saved to (default: `data_dir/cache`).
+    pub cache_dir: *const c_char,
+    /// Database file (default: `data_dir/nodalync.db`).
+    pub database_path: *const c_char,
+    /// Password protecting the identity (required).
+    pub password: *const c_char,
+    /// Connect to the network.
+    pub enable_network: bool,
+    /// Newline-separated bootstrap multiaddrs, or NULL for none.
+    pub bootstrap_nodes: *const c_char,
+}
+
+/// An open embedded node.
+pub struct NodalyncNode {
+    embedder: Embedder,
+}
+
+/// Open a node, creating its identity on first use.
+///
+/// Returns NULL on failure. Release the node with [`nodalync_node_free`].
+///
+/// # Safety
+///
+/// `config` must point to a valid [`NodalyncConfig`] whose non-NULL
+/// strings are valid NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn nodalync_node_open(config: *const NodalyncConfig) -> *mut NodalyncNode {
+    call(|| {
+        let config = config
+            .as_ref()
+            .ok_or_else(|| FfiError::invalid("config is NULL"))?;
+
+        let mut embedder_config = EmbedderConfig::new(
+            required_str(config.data_dir, "data_dir")?,
+            required_str(config.password, "password")?,
+        );
+        embedder_config.content_dir = optional_str(config.content_dir)?.map(PathBuf::from);
+        embedder_config.cache_dir = optional_str(config.cache_dir)?.map(PathBuf::from);
+        embedder_config.database_path = optional_str(config.database_path)?.map(PathBuf::from);
+        embedder_config.enable_network = config.enable_network;
+        embedder_config.bootstrap_nodes = optional_str(config.bootstrap_nodes)?
+            .map(|nodes| {
+                nodes
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let embedder = Embedder::open(embedder_config)?;
+        Ok(Box::into_raw(Box::new(NodalyncNode { embedder })))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Close a node and release its handle. NULL is ignored.
+///
+/// # Safety
+///
+/// `node` must be NULL or a handle from [`nodalync_node_open`] that has not
+/// been freed.
+#[no_mangle]
+pub unsafe extern "C" fn nodalync_node_free(node: *mut NodalyncNode) {
+    if !node.is_null() {
+        drop(Box::from_raw(node));
+    }
+}
+
+/// Our peer ID, in `ndl1...` form.
+///
+/// # Safety
+///
+/// `node` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn nodalync_node_peer_id(node: *const NodalyncNode) -> *mut c_char {
+    call(|| into_c_string(node_ref(node)?.embedder.peer_id())).unwrap_or(ptr::null_mut())
+}
+
+/// Create and publish content as shared, returning its hash.
+///
+/// `price` is in tinybars.
+///
+/// # Safety
+///
+/// `node` must be a valid handle, `content` must point to `content_len`
+/// readable bytes, and `title` must be a valid string.
+#[no_mangle]
+pub unsafe extern "C" fn nodalync_node_publish(
+    node: *const NodalyncNode,
+    content: *const u8,
+    content_len: usize,
+    title: *const c_char,
+    price: u64,
+) -> *mut c_char {
+    call(|| {
+        let node = node_ref(node)?;
+        if content.is_null() {
+            return Err(FfiError::invalid("content is NULL"));
+        }
+        let content = std::slice::from_raw_parts(content, content_len);
+        let title = required_str(title, "title")?;
+        into_c_string(node.embedder.publish(content, &title, price)?)
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Preview content as a JSON object.
+///
+/// # Safety
+///
+/// `node` must be a valid handle and `hash` a valid string.
+#[no_mangle]
+pub unsafe extern "C" fn nodalync_node_preview(
+    node: *const NodalyncNode,
+    hash: *const c_char,
+) -> *mut c_char {
+    call(|| {
+        let node = node_ref(node)?;
+        let hash = required_str(hash, "hash")?;
+        into_json(&node.embedder.preview(&hash)?)
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Query (and pay for) content, returning the result as a JSON object.
+///
+/// The content is written to `output_path`, or to the cache directory when
+/// it is NULL.
+///
+/// # Safety
+///
+/// `node` must be a valid handle, `hash` a valid string, and `output_path`
+/// NULL or a valid string.
+#[no_mangle]
+pub unsafe extern "C" fn nodalync_node_query(
+    node: *const NodalyncNode,
+    hash: *const c_char,
+    output_path: *const c_char,
+) -> *mut c_char {
+    call(|| {
+        let node = node_ref(node)?;
+        let hash = required_str(hash, "hash")?;
+        let output = optional_str(output_path)?.map(PathBuf::from);
+        into_json(&node.embedder.query(&hash, output.as_deref())?)
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Open payment channels as a JSON array.
+///
+/// # Safety
+///
+/// `node` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn nodalync_node_channels(node: *const NodalyncNode) -> *mut c_char {
+    call(|| into_json(&node_ref(node)?.embedder.channels()?)).unwrap_or(ptr::null_mut())
+}
+
+/// Release a string returned by this library. NULL is ignored.
+///
+/// # Safety
+///
+/// `s` must be NULL or a string returned by this library that has not been
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn nodalync_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// The last error on this thread, or NULL if there was none.
+///
+/// The string is owned by the library and valid until the next call on
+/// this thread.
+#[no_mangle]
+pub extern "C" fn nodalync_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Run an FFI call, recording its error (or panic) as the last error.
+fn call<T>(f: impl FnOnce() -> FfiResult<T>) -> Option<T> {
+    let result = catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "panic".to_string());
+        Err(FfiError::Panic(message))
+    });
+
+    let (value, error) = match result {
+        Ok(value) => (Some(value), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+    LAST_ERROR.with(|last| {
+        *last.borrow_mut() =
+            error.map(|e| CString::new(e.replace('\0', " ")).expect("NULs replaced"));
+    });
+    value
+}
+
+unsafe fn node_ref<'a>(node: *const NodalyncNode) -> FfiResult<&'a NodalyncNode> {
+    node.as_ref()
+        .ok_or_else(|| FfiError::invalid("node is NULL"))
+}
+
+unsafe fn required_str(s: *const c_char, name: &str) -> FfiResult<String> {
+    optional_str(s)?.ok_or_else(|| FfiError::invalid(format!("{} is NULL", name)))
+}
+
+unsafe fn optional_str(s: *const c_char) -> FfiResult<Option<String>> {
+    if s.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map(|s| Some(s.to_string()))
+        .map_err(|_| FfiError::invalid("string is not valid UTF-8"))
+}
+
+fn into_c_string(s: String) -> FfiResult<*mut c_char> {
+    CString::new(s)
+        .map(CString::into_raw)
+        .map_err(|_| FfiError::invalid("string contains NUL"))
+}
+
+fn into_json<T: Serialize>(value: &T) -> FfiResult<*mut c_char> {
+    into_c_string(serde_json::to_string(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    unsafe fn take_string(s: *mut c_char) -> String {
+        assert!(!s.is_null(), "unexpected NULL: {:?}", last_error());
+        let value = CStr::from_ptr(s).to_str().unwrap().to_string();
+        nodalync_string_free(s);
+        value
+    }
+
+    fn last_error() -> Option<String> {
+        let error = nodalync_last_error();
+        (!error.is_null()).then(|| unsafe { CStr::from_ptr(error) }.to_string_lossy().into())
+    }
+
+    #[test]
+    fn test_node_lifecycle() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = CString::new(temp_dir.path().to_str().unwrap()).unwrap();
+        let password = CString::new("password").unwrap();
+        let config = NodalyncConfig {
+            data_dir: data_dir.as_ptr(),
+            content_dir: ptr::null(),
+            cache_dir: ptr::null(),
+            database_path: ptr::null(),
+            password: password.as_ptr(),
+            enable_network: false,
+            bootstrap_nodes: ptr::null(),
+        };
+
+        unsafe {
+            let node = nodalync_node_open(&config);
+            assert!(!node.is_null(), "{:?}", last_error());
+            assert!(take_string(nodalync_node_peer_id(node)).starts_with("ndl1"));
+
+            let content = b"Embedded content";
+            let title = CString::new("Embedded").unwrap();
+            let hash = take_string(nodalync_node_publish(
+                node,
+                content.as_ptr(),
+                content.len(),
+                title.as_ptr(),
+                0,
+            ));
+            let hash = CString::new(hash).unwrap();
+
+            let preview = take_string(nodalync_node_preview(node, hash.as_ptr()));
+            let preview: serde_json::Value = serde_json::from_str(&preview).unwrap();
+            assert_eq!(preview["title"], "Embedded");
+
+            let result = take_string(nodalync_node_query(node, hash.as_ptr(), ptr::null()));
+            let result: serde_json::Value = serde_json::from_str(&result).unwrap();
+            assert_eq!(result["price_paid"], 0);
+
+            assert_eq!(take_string(nodalync_node_channels(node)), "[]");
+            assert!(last_error().is_none());
+
+            nodalync_node_free(node);
+        }
+    }
+
+    #[test]
+    fn test_errors_reported() {
+        unsafe {
+            assert!(nodalync_node_open(ptr::null()).is_null());
+            assert_eq!(
+                last_error().as_deref(),
+                Some("invalid argument: config is NULL")
+            );
+
+            assert!(nodalync_node_peer_id(ptr::null()).is_null());
+            assert!(last_error().unwrap().contains("node is NULL"));
+
+            nodalync_node_free(ptr::null_mut());
+            nodalync_string_free(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! C bindings for embedding a Nodalync node in mobile apps.
+//!
+//! This crate exposes a trimmed, consumer-oriented node to iOS and Android
+//! apps through a C interface: identity, publishing, preview, query and
+//! channel status. The host app supplies the storage paths (inside its
+//! sandbox) and the identity password.
+//!
+//! # Building
+//!
+//! ```bash
+//! # iOS (static library)
+//! cargo build --release -p nodalync-ffi --target aarch64-apple-ios
+//! # Android (shared library)
+//! cargo build --release -p nodalync-ffi --target aarch64-linux-android
+//! ```
+//!
+//! The C header is `include/nodalync.h`, generated with
+//! `cbindgen --config cbindgen.toml --output include/nodalync.h` from this
+//! crate's directory.
+//!
+//! # Usage
+//!
+//! ```c
+//! NodalyncConfig config = {
+//!     .data_dir = app_support_dir,
+//!     .password = password,
+//!     .enable_network = true,
+//!     .bootstrap_nodes = "/dns4/.../tcp/9000/p2p/12D3KooW...",
+//! };
+//! NodalyncNode *node = nodalync_node_open(&config);
+//! if (!node) { log(nodalync_last_error()); }
+//!
+//! char *preview = nodalync_node_preview(node, hash);  // JSON
+//! nodalync_string_free(preview);
+//! nodalync_node_free(node);
+//! ```
+//!
+//! The same operations are available to Rust hosts through [`Embedder`].
+
+pub mod embedder;
+pub mod error;
+pub mod ffi;
+
+pub use embedder::{ChannelInfo, Embedder, EmbedderConfig, PreviewInfo, QueryInfo};
+pub use error::{FfiError, FfiResult};
@@ -0,0 +1,36 @@
+"""Type stubs for the nodalync extension module."""
+
+from os import PathLike
+from typing import Any, Optional, Union
+
+__version__: str
+
+Json = Union[str, dict, list]
+Path = Union[str, PathLike]
+
+class ValidationFailed(Exception):
+    """Content or a manifest failed protocol validation."""
+
+class NodeError(Exception):
+    """The local node could not be reached or reported an error."""
+
+def content_hash(content: bytes) -> str: ...
+def create_manifest(
+    content: bytes,
+    owner: str,
+    title: str,
+    description: Optional[str] = None,
+    tags: list[str] = ...,
+    timestamp: Optional[int] = None,
+) -> dict[str, Any]: ...
+def validate_content(content: bytes, manifest: Json) -> None: ...
+def validate_manifest(
+    manifest: Json, previous: Optional[Json] = None, sources: Optional[Json] = None
+) -> None: ...
+def verify_signature(public_key: str, message: bytes, signature: str) -> bool: ...
+def verify_merkle_proof(root: str, entry: Json, proof: Json) -> bool: ...
+def preview(hash: str, data_dir: Optional[Path] = None) -> dict[str, Any]: ...
+def query(
+    hash: str, output: Optional[Path] = None, data_dir: Optional[Path] = None
+) -> dict[str, Any]: ...
+def node_status(data_dir: Optional[Path] = None) -> dict[str, Any]: ...
@@ -0,0 +1,195 @@
+//! Binding operations on plain Rust values.
+//!
+//! Structured values (manifests, settlement entries, proofs) cross the
+//! boundary as JSON in the same form the protocol types serialize to, so
+//! the Python layer only converts between JSON text and Python objects.
+
+use nodalync_crypto::{current_timestamp, peer_id_from_string, verify, Hash, Timestamp};
+use nodalync_econ::MerkleProof;
+use nodalync_types::{Manifest, Metadata, SettlementEntry};
+use serde::de::DeserializeOwned;
+
+use crate::error::{BindingError, BindingResult};
+
+/// Hash content, returning the hex-encoded content hash.
+pub fn content_hash(content: &[u8]) -> String {
+    nodalync_crypto::content_hash(content).to_string()
+}
+
+/// Create a version-1 L0 manifest for `content`, returned as JSON.
+///
+/// The manifest is private with default economics, as `nodalync publish`
+/// creates it before visibility and price are applied.
+pub fn create_manifest(
+    content: &[u8],
+    owner: &str,
+    title: &str,
+    description: Option<&str>,
+    tags: Vec<String>,
+    timestamp: Option<Timestamp>,
+) -> BindingResult<String> {
+    let owner = peer_id_from_string(owner).map_err(|e| BindingError::invalid("owner", e))?;
+    let mut metadata = Metadata::new(title, content.len() as u64).with_tags(tags);
+    if let Some(description) = description {
+        metadata = metadata.with_description(description);
+    }
+
+    let manifest = Manifest::new_l0(
+        nodalync_crypto::content_hash(content),
+        owner,
+        metadata,
+        timestamp.unwrap_or_else(current_timestamp),
+    );
+    nodalync_valid::validate_metadata(&manifest)?;
+    to_json("manifest", &manifest)
+}
+
+/// Validate content against its manifest (§9.1).
+pub fn validate_content(content: &[u8], manifest: &str) -> BindingResult<()> {
+    let manifest: Manifest = parse_json("manifest", manifest)?;
+    nodalync_valid::validate_content(content, &manifest)?;
+    Ok(())
+}
+
+/// Validate a manifest on its own.
+///
+/// Checks metadata constraints, the version chain against `previous` (§9.2)
+/// and provenance against `sources` (§9.3). `sources` is a JSON array of
+/// manifests; omitting it checks provenance without source manifests.
+pub fn validate_manifest(
+    manifest: &str,
+    previous: Option<&str>,
+    sources: Option<&str>,
+) -> BindingResult<()> {
+    let manifest: Manifest = parse_json("manifest", manifest)?;
+    let previous: Option<Manifest> = previous
+        .map(|p| parse_json("previous manifest", p))
+        .transpose()?;
+    let sources: Vec<Manifest> = sources
+        .map(|s| parse_json("source manifests", s))
+        .transpose()?
+        .unwrap_or_default();
+
+    nodalync_valid::validate_metadata(&manifest)?;
+    nodalync_valid::validate_version(&manifest, previous.as_ref())?;
+    nodalync_valid::validate_provenance(&manifest, &sources)?;
+    Ok(())
+}
+
+/// Verify an Ed25519 signature over `message`.
+///
+/// The key and signature are hex-encoded, as they appear in manifests and
+/// announcements.
+pub fn verify_signature(public_key: &str, message: &[u8], signature: &str) -> BindingResult<bool> {
+    let public_key = parse_json_str("public key", public_key)?;
+    let signature = parse_json_str("signature", signature)?;
+    Ok(verify(&public_key, message, &signature))
+}
+
+/// Verify that a settlement entry is included under a batch merkle root.
+pub fn verify_merkle_proof(root: &str, entry: &str, proof: &str) -> BindingResult<bool> {
+    let root: Hash = parse_json_str("merkle root", root)?;
+    let entry: SettlementEntry = parse_json("settlement entry", entry)?;
+    let proof: MerkleProof = parse_json("merkle proof", proof)?;
+    Ok(nodalync_econ::verify_merkle_proof(&root, &entry, &proof))
+}
+
+/// Parse a JSON document.
+fn parse_json<T: DeserializeOwned>(what: &'static str, json: &str) -> BindingResult<T> {
+    serde_json::from_str(json).map_err(|e| BindingError::invalid(what, e))
+}
+
+/// Parse a value from its JSON string form (hex for hashes and keys).
+fn parse_json_str<T: DeserializeOwned>(what: &'static str, s: &str) -> BindingResult<T> {
+    serde_json::from_value(serde_json::Value::String(s.to_string()))
+        .map_err(|e| BindingError::invalid(what, e))
+}
+
+/// Serialize a value to JSON.
+fn to_json<T: serde::Serialize>(what: &'static str, value: &T) -> BindingResult<String> {
+    serde_json::to_string(value).map_err(|e| BindingError::invalid(what, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::PeerId;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key, peer_id_to_string, sign};
+    use nodalync_econ::{compute_merkle_root, create_merkle_proof};
+
+    fn test_owner() -> String {
+        let (_, public_key) = generate_identity();
+        peer_id_to_string(&peer_id_from_public_key(&public_key))
+    }
+
+    #[test]
+    fn test_create_and_validate_manifest() {
+        let content = b"Attribution for the pipeline";
+        let manifest = create_manifest(
+            content,
+            &test_owner(),
+            "Pipeline notes",
+            Some("Notes"),
+            vec!["etl".to_string()],
+            Some(1_000),
+        )
+        .unwrap();
+
+        let parsed: Manifest = serde_json::from_str(&manifest).unwrap();
+        assert_eq!(parsed.hash.to_string(), content_hash(content));
+        assert_eq!(parsed.metadata.tags, vec!["etl".to_string()]);
+
+        validate_content(content, &manifest).unwrap();
+        validate_manifest(&manifest, None, None).unwrap();
+        assert!(matches!(
+            validate_content(b"tampered", &manifest),
+            Err(BindingError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        assert!(matches!(
+            create_manifest(b"x", "not-a-peer", "Title", None, vec![], None),
+            Err(BindingError::InvalidArgument { what: "owner", .. })
+        ));
+        assert!(matches!(
+            validate_content(b"x", "{}"),
+            Err(BindingError::InvalidArgument {
+                what: "manifest",
+                ..
+            })
+        ));
+        assert!(matches!(
+            validate_manifest("[]", None, None),
+            Err(BindingError::InvalidArgument { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let (private_key, public_key) = generate_identity();
+        let signature = sign(&private_key, b"message");
+        let public_key = serde_json::to_value(public_key).unwrap();
+        let signature = serde_json::to_value(signature).unwrap();
+        let (public_key, signature) = (public_key.as_str().unwrap(), signature.as_str().unwrap());
+
+        assert!(verify_signature(public_key, b"message", signature).unwrap());
+        assert!(!verify_signature(public_key, b"other", signature).unwrap());
+        assert!(verify_signature("zz", b"message", signature).is_err());
+    }
+
+    #[test]
+    fn test_verify_merkle_proof() {
+        let entries: Vec<SettlementEntry> = (0..3u8)
+            .map(|i| SettlementEntry::new(PeerId::from_bytes([i; 20]), 100, vec![], vec![]))
+            .collect();
+        let root = compute_merkle_root(&entries).to_string();
+        let proof = serde_json::to_string(&create_merkle_proof(&entries, 1).unwrap()).unwrap();
+        let entry = serde_json::to_string(&entries[1]).unwrap();
+        let other = serde_json::to_string(&entries[2]).unwrap();
+
+        assert!(verify_merkle_proof(&root, &entry, &proof).unwrap());
+        assert!(!verify_merkle_proof(&root, &other, &proof).unwrap());
+    }
+}
@@ -0,0 +1,63 @@
+//! Error types for the Python bindings.
+
+use nodalync_valid::ValidationError;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::{create_exception, PyErr};
+use thiserror::Error;
+
+create_exception!(
+    nodalync,
+    ValidationFailed,
+    PyException,
+    "Content or a manifest failed protocol validation."
+);
+create_exception!(
+    nodalync,
+    NodeError,
+    PyException,
+    "The local node could not be reached or reported an error."
+);
+
+/// Result type for binding operations.
+pub type BindingResult<T> = Result<T, BindingError>;
+
+/// Errors surfaced to Python.
+#[derive(Debug, Error)]
+pub enum BindingError {
+    /// An argument could not be parsed (raised as `ValueError`).
+    #[error("invalid {what}: {reason}")]
+    InvalidArgument {
+        /// What was being parsed.
+        what: &'static str,
+        /// Why parsing failed.
+        reason: String,
+    },
+
+    /// Validation failed (raised as `ValidationFailed`).
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+
+    /// The node was unreachable or answered with an error (raised as `NodeError`).
+    #[error("{0}")]
+    Node(String),
+}
+
+impl BindingError {
+    /// Create an invalid argument error.
+    pub fn invalid(what: &'static str, reason: impl ToString) -> Self {
+        Self::InvalidArgument {
+            what,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl From<BindingError> for PyErr {
+    fn from(err: BindingError) -> Self {
+        match err {
+            BindingError::InvalidArgument { .. } => PyValueError::new_err(err.to_string()),
+            BindingError::Validation(_) => ValidationFailed::new_err(err.to_string()),
+            BindingError::Node(_) => NodeError::new_err(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! Python bindings for Nodalync.
+//!
+//! This crate builds the `nodalync` Python extension module, letting data
+//! pipelines hash content, create and validate manifests, verify signatures
+//! and settlement merkle proofs, and preview or query content through a
+//! running node, without shelling out to the CLI.
+//!
+//! # Building
+//!
+//! ```bash
+//! pip install maturin
+//! maturin develop -m crates/bindings/nodalync-py/Cargo.toml
+//! ```
+//!
+//! # Usage
+//!
+//! ```python
+//! import nodalync
+//!
+//! content = open("report.csv", "rb").read()
+//! manifest = nodalync.create_manifest(content, owner="ndl1...", title="Q3 report")
+//! nodalync.validate_content(content, manifest)  # raises ValidationFailed
+//!
+//! preview = nodalync.preview(source_hash)  # needs `nodalync start`
+//! ```
+//!
+//! Manifests, settlement entries and merkle proofs are passed as dicts (or
+//! JSON strings) in the protocol's JSON form; hashes, keys and signatures
+//! are hex strings.
+//!
+//! # Errors
+//!
+//! - Malformed arguments raise `ValueError`
+//! - Validation failures raise `nodalync.ValidationFailed`
+//! - An unreachable node or a failed node request raises `nodalync.NodeError`
+
+use std::path::PathBuf;
+
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+
+pub mod api;
+pub mod error;
+pub mod node;
+
+pub use error::{BindingError, BindingResult, NodeError, ValidationFailed};
+pub use node::NodeClient;
+
+/// Hash content, returning the hex-encoded content hash.
+#[pyfunction]
+fn content_hash(content: &[u8]) -> String {
+    api::content_hash(content)
+}
+
+/// Create a version-1 L0 manifest for `content`.
+#[pyfunction]
+#[pyo3(signature = (content, owner, title, description=None, tags=Vec::new(), timestamp=None))]
+fn create_manifest(
+    py: Python<'_>,
+    content: &[u8],
+    owner: &str,
+    title: &str,
+    description: Option<&str>,
+    tags: Vec<String>,
+    timestamp: Option<u64>,
+) -> PyResult<PyObject> {
+    let manifest = api::create_manifest(content, owner, title, description, tags, timestamp)?;
+    json_loads(py, &manifest)
+}
+
+/// Validate content against its manifest.
+#[pyfunction]
+fn validate_content(content: &[u8], manifest: &Bound<'_, PyAny>) -> PyResult<()> {
+    Ok(api::validate_content(content, &json_dumps(manifest)?)?)
+}
+
+/// Validate a manifest's metadata, version chain and provenance.
+#[pyfunction]
+#[pyo3(signature = (manifest, previous=None, sources=None))]
+fn validate_manifest(
+    manifest: &Bound<'_, PyAny>,
+    previous: Option<&Bound<'_, PyAny>>,
+    sources: Option<&Bound<'_, PyAny>>,
+) -> PyResult<()> {
+    let previous = previous.map(json_dumps).transpose()?;
+    let sources = sources.map(json_dumps).transpose()?;
+    Ok(api::validate_manifest(
+        &json_dumps(manifest)?,
+        previous.as_deref(),
+        sources.as_deref(),
+    )?)
+}
+
+/// Verify an Ed25519 signature over `message`.
+#[pyfunction]
+fn verify_signature(public_key: &str, message: &[u8], signature: &str) -> PyResult<bool> {
+    Ok(api::verify_signature(public_key, message, signature)?)
+}
+
+/// Verify that a settlement entry is included under a batch merkle root.
+#[pyfunction]
+fn verify_merkle_proof(
+    root: &str,
+    entry: &Bound<'_, PyAny>,
+    proof: &Bound<'_, PyAny>,
+) -> PyResult<bool> {
+    Ok(api::verify_merkle_proof(
+        root,
+        &json_dumps(entry)?,
+        &json_dumps(proof)?,
+    )?)
+}
+
+/// Preview content through the running node.
+#[pyfunction]
+#[pyo3(signature = (hash, data_dir=None))]
+fn preview(py: Python<'_>, hash: &str, data_dir: Option<PathBuf>) -> PyResult<PyObject> {
+    let client = NodeClient::new(data_dir.as_deref());
+    let output = py.allow_threads(|| client.preview(hash))?;
+    json_loads(py, &output.to_string())
+}
+
+/// Query (and pay for) content through the running node.
+#[pyfunction]
+#[pyo3(signature = (hash, output=None, data_dir=None))]
+fn query(
+    py: Python<'_>,
+    hash: &str,
+    output: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+) -> PyResult<PyObject> {
+    let client = NodeClient::new(data_dir.as_deref());
+    let result = py.allow_threads(|| client.query(hash, output.as_deref()))?;
+    json_loads(py, &result.to_string())
+}
+
+/// Status of the running node.
+#[pyfunction]
+#[pyo3(signature = (data_dir=None))]
+fn node_status(py: Python<'_>, data_dir: Option<PathBuf>) -> PyResult<PyObject> {
+    let client = NodeClient::new(data_dir.as_deref());
+    let status = py.allow_threads(|| client.status())?;
+    json_loads(py, &status.to_string())
+}
+
+/// JSON text for a dict/list argument; strings are taken as JSON already.
+fn json_dumps(value: &Bound<'_, PyAny>) -> PyResult<String> {
+    if let Ok(s) = value.downcast::<PyString>() {
+        return Ok(s.to_str()?.to_string());
+    }
+    let json = value.py().import("json")?;
+    json.call_method1("dumps", (value,))?.extract()
+}
+
+/// Python object for JSON text.
+fn json_loads(py: Python<'_>, json: &str) -> PyResult<PyObject> {
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+/// The `nodalync` Python module.
+#[pymodule]
+fn nodalync(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add("ValidationFailed", m.py().get_type::<ValidationFailed>())?;
+    m.add("NodeError", m.py().get_type::<NodeError>())?;
+    m.add_function(wrap_pyfunction!(content_hash, m)?)?;
+    m.add_function(wrap_pyfunction!(create_manifest, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_content, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_manifest, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_signature, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_merkle_proof, m)?)?;
+    m.add_function(wrap_pyfunction!(preview, m)?)?;
+    m.add_function(wrap_pyfunction!(query, m)?)?;
+    m.add_function(wrap_pyfunction!(node_status, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,188 @@
+//! Requests to a running node over its control socket.
+//!
+//! A node started with `nodalync start` listens on `<data_dir>/run/node.sock`
+//! (see the CLI's control interface). Each request is one JSON line asking
+//! for JSON output; the node answers with one JSON line whose `output` is
+//! the command's JSON rendering. Only Unix sockets are supported.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::error::{BindingError, BindingResult};
+
+/// Control socket file name, as created by the node.
+const SOCKET_FILE_NAME: &str = "node.sock";
+
+/// Directory the node creates its control socket in.
+const SOCKET_DIR_NAME: &str = "run";
+
+/// How long to wait for the node to answer.
+///
+/// Generous because queries involve network round trips and payment.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Client for a running node's control socket.
+#[derive(Debug, Clone)]
+pub struct NodeClient {
+    socket: PathBuf,
+}
+
+impl NodeClient {
+    /// Create a client for the node using `data_dir`, or the default data
+    /// directory (`NODALYNC_DATA_DIR` or the platform data dir).
+    pub fn new(data_dir: Option<&Path>) -> Self {
+        let data_dir = data_dir
+            .map(Path::to_path_buf)
+            .unwrap_or_else(nodalync_store::default_data_dir);
+        Self {
+            socket: data_dir.join(SOCKET_DIR_NAME).join(SOCKET_FILE_NAME),
+        }
+    }
+
+    /// Preview content: its manifest summary and L1 mentions.
+    pub fn preview(&self, hash: &str) -> BindingResult<Value> {
+        self.request(json!({ "command": "preview", "hash": hash }))
+    }
+
+    /// Query (and pay for) content.
+    ///
+    /// When `output` is given the node writes the content there; it must be
+    /// an absolute path the node can write to.
+    pub fn query(&self, hash: &str, output: Option<&Path>) -> BindingResult<Value> {
+        if let Some(output) = output {
+            if !output.is_absolute() {
+                return Err(BindingError::invalid("output", "path must be absolute"));
+            }
+        }
+        self.request(json!({ "command": "query", "hash": hash, "output": output }))
+    }
+
+    /// Node status.
+    pub fn status(&self) -> BindingResult<Value> {
+        self.request(json!({ "command": "status" }))
+    }
+
+    /// Send one request and parse the node's JSON output.
+    #[cfg(unix)]
+    fn request(&self, mut request: Value) -> BindingResult<Value> {
+        use std::io::{BufRead, BufReader, Write};
+        use std::os::unix::net::UnixStream;
+
+        request["format"] = json!("json");
+
+        let stream = UnixStream::connect(&self.socket).map_err(|e| {
+            BindingError::Node(format!(
+                "no running node at {}: {}",
+                self.socket.display(),
+                e
+            ))
+        })?;
+        stream
+            .set_read_timeout(Some(RESPONSE_TIMEOUT))
+            .map_err(node_io)?;
+
+        let mut line = request.to_string();
+        line.push('\n');
+        (&stream).write_all(line.as_bytes()).map_err(node_io)?;
+
+        let mut response = String::new();
+        BufReader::new(&stream)
+            .read_line(&mut response)
+            .map_err(node_io)?;
+        parse_response(&response)
+    }
+
+    #[cfg(not(unix))]
+    fn request(&self, _request: Value) -> BindingResult<Value> {
+        Err(BindingError::Node(
+            "node requests are only supported on Unix".to_string(),
+        ))
+    }
+}
+
+/// Parse a control response into the command's JSON output.
+fn parse_response(response: &str) -> BindingResult<Value> {
+    let response: Value = serde_json::from_str(response)
+        .map_err(|e| BindingError::Node(format!("invalid response from node: {}", e)))?;
+
+    match response["status"].as_str() {
+        Some("ok") => {
+            let output = response["output"].as_str().unwrap_or_default();
+            serde_json::from_str(output)
+                .map_err(|e| BindingError::Node(format!("invalid output from node: {}", e)))
+        }
+        Some("error") => Err(BindingError::Node(
+            response["message"]
+                .as_str()
+                .unwrap_or("unknown error")
+                .to_string(),
+        )),
+        _ => Err(BindingError::Node(
+            "invalid response from node: missing status".to_string(),
+        )),
+    }
+}
+
+#[cfg(unix)]
+fn node_io(e: std::io::Error) -> BindingError {
+    BindingError::Node(format!("control socket error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response() {
+        let ok = parse_response(r#"{"status":"ok","output":"{\"title\":\"Notes\"}"}"#).unwrap();
+        assert_eq!(ok["title"], "Notes");
+
+        let err = parse_response(r#"{"status":"error","message":"not found"}"#);
+        assert!(matches!(err, Err(BindingError::Node(m)) if m == "not found"));
+
+        assert!(parse_response("garbage").is_err());
+    }
+
+    #[test]
+    fn test_no_running_node() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let client = NodeClient::new(Some(temp_dir.path()));
+        assert!(matches!(client.status(), Err(BindingError::Node(_))));
+        assert!(matches!(
+            client.query(&"0".repeat(64), Some(Path::new("relative.txt"))),
+            Err(BindingError::InvalidArgument { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_request_roundtrip() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::os::unix::net::UnixListener;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let socket_dir = temp_dir.path().join(SOCKET_DIR_NAME);
+        std::fs::create_dir(&socket_dir).unwrap();
+        let listener = UnixListener::bind(socket_dir.join(SOCKET_FILE_NAME)).unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut request = String::new();
+            BufReader::new(&stream).read_line(&mut request).unwrap();
+            let output = json!({ "request": serde_json::from_str::<Value>(&request).unwrap() });
+            let response = json!({ "status": "ok", "output": output.to_string() });
+            (&stream)
+                .write_all(format!("{}\n", response).as_bytes())
+                .unwrap();
+        });
+
+        let client = NodeClient::new(Some(temp_dir.path()));
+        let output = client.preview("abc").unwrap();
+        server.join().unwrap();
+
+        assert_eq!(output["request"]["command"], "preview");
+        assert_eq!(output["request"]["hash"], "abc");
+        assert_eq!(output["request"]["format"], "json");
+    }
+}
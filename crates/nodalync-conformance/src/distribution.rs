@@ -0,0 +1,67 @@
+//! Revenue distribution vectors (§10.1).
+
+use nodalync_crypto::PeerId;
+use nodalync_econ::distribute_revenue;
+use nodalync_types::{Amount, ProvenanceEntry};
+use serde::{Deserialize, Serialize};
+
+use crate::{expect_eq, Failure, Vector};
+
+/// Suite name.
+pub const SUITE: &str = "distribution";
+
+const VECTORS: &str = include_str!("../vectors/distribution.json");
+
+/// One recipient's share of a payment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Share {
+    /// Recipient
+    pub recipient: PeerId,
+    /// Amount paid to the recipient
+    pub amount: Amount,
+}
+
+/// A payment for content and how it is distributed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DistributionVector {
+    /// What the vector exercises
+    pub description: String,
+    /// Payment amount
+    pub payment: Amount,
+    /// Owner of the queried content
+    pub owner: PeerId,
+    /// Provenance of the queried content
+    pub provenance: Vec<ProvenanceEntry>,
+    /// Expected shares, ordered by recipient
+    pub expected: Vec<Share>,
+}
+
+impl Vector for DistributionVector {
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// The distribution vectors.
+pub fn vectors() -> Vec<DistributionVector> {
+    crate::load(SUITE, VECTORS)
+}
+
+/// Check one vector.
+pub fn check(vector: &DistributionVector) -> Result<(), String> {
+    let actual: Vec<Share> = distribute_revenue(vector.payment, &vector.owner, &vector.provenance)
+        .into_iter()
+        .map(|d| Share {
+            recipient: d.recipient,
+            amount: d.amount,
+        })
+        .collect();
+    expect_eq("shares", &vector.expected, &actual)?;
+    let total: Amount = actual.iter().map(|s| s.amount).sum();
+    expect_eq("total distributed", &vector.payment, &total)
+}
+
+/// Check every distribution vector.
+pub fn run() -> Vec<Failure> {
+    crate::run_suite(SUITE, &vectors(), check)
+}
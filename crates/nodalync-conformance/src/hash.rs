@@ -0,0 +1,107 @@
+//! Hash vectors (§3.1, Appendix A).
+
+use nodalync_crypto::{chunk_hash, content_hash, section_hash, Hash};
+use nodalync_types::Amount;
+use nodalync_wire::{channel_state_hash, ChannelBalances};
+use serde::{Deserialize, Serialize};
+
+use crate::{decode_hex, expect_eq, Failure, Vector};
+
+/// Suite name.
+pub const SUITE: &str = "hash";
+
+const VECTORS: &str = include_str!("../vectors/hash.json");
+
+/// What is hashed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HashInput {
+    /// `H(0x00 || len || content)`
+    Content {
+        /// Content bytes (hex)
+        input: String,
+    },
+    /// Hash of chunk `index` of content
+    Chunk {
+        /// Chunk index
+        index: u32,
+        /// Chunk bytes (hex)
+        input: String,
+    },
+    /// Hash of the section at byte `offset` of content
+    Section {
+        /// Byte offset of the section
+        offset: u64,
+        /// Section bytes (hex)
+        input: String,
+    },
+    /// `H(0x02 || channel_id || nonce || initiator || responder)`
+    ChannelState {
+        /// Channel ID
+        channel_id: Hash,
+        /// State nonce
+        nonce: u64,
+        /// Initiator's balance
+        initiator: Amount,
+        /// Responder's balance
+        responder: Amount,
+    },
+}
+
+/// A hash input and its expected hash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HashVector {
+    /// What the vector exercises
+    pub description: String,
+    /// What is hashed
+    #[serde(flatten)]
+    pub input: HashInput,
+    /// Expected hash
+    pub expected: Hash,
+}
+
+impl Vector for HashVector {
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// The hash vectors.
+pub fn vectors() -> Vec<HashVector> {
+    crate::load(SUITE, VECTORS)
+}
+
+/// Check one vector.
+pub fn check(vector: &HashVector) -> Result<(), String> {
+    let actual = match &vector.input {
+        HashInput::Content { input } => {
+            let content = decode_hex("input", input)?;
+            // The wire crate hashes payloads with its own copy of the
+            // function; both must agree
+            expect_eq(
+                "wire content hash",
+                &vector.expected,
+                &nodalync_wire::content_hash(&content),
+            )?;
+            content_hash(&content)
+        }
+        HashInput::Chunk { index, input } => chunk_hash(*index, &decode_hex("input", input)?),
+        HashInput::Section { offset, input } => section_hash(*offset, &decode_hex("input", input)?),
+        HashInput::ChannelState {
+            channel_id,
+            nonce,
+            initiator,
+            responder,
+        } => channel_state_hash(
+            channel_id,
+            *nonce,
+            &ChannelBalances::new(*initiator, *responder),
+        ),
+    };
+    expect_eq("hash", &vector.expected, &actual)
+}
+
+/// Check every hash vector.
+pub fn run() -> Vec<Failure> {
+    crate::run_suite(SUITE, &vectors(), check)
+}
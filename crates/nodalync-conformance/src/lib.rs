@@ -0,0 +1,125 @@
+//! Protocol conformance test vectors for Nodalync.
+//!
+//! The vectors in `vectors/` are the canonical expected outputs of the
+//! protocol's deterministic functions. They are plain JSON, with byte
+//! strings, hashes, keys and signatures in lowercase hex, so an
+//! independent implementation can load the same files and check itself
+//! against them.
+//!
+//! # Suites
+//!
+//! | Suite | File | Covers |
+//! |-------|------|--------|
+//! | `hash` | `hash.json` | Content, chunk, section and channel state hashes (§3.1, Appendix A) |
+//! | `signature` | `signature.json` | Ed25519 keys, peer IDs and signatures (§3.2, §3.3) |
+//! | `message` | `message.json` | Signed message envelopes and their encoding (§6.1) |
+//! | `payload` | `payload.json` | CBOR payload encodings (§6) |
+//! | `distribution` | `distribution.json` | Revenue distribution (§10.1) |
+//! | `merkle` | `merkle.json` | Settlement merkle roots, batch IDs and proofs (§10.4) |
+//!
+//! Each suite module loads its vectors with `vectors()` and checks this
+//! implementation against them with `run()`; [`run_all`] runs every
+//! suite.
+//!
+//! # Example
+//!
+//! ```
+//! let failures = nodalync_conformance::run_all();
+//! assert!(failures.is_empty(), "{:?}", failures);
+//! ```
+
+pub mod distribution;
+pub mod hash;
+pub mod merkle;
+pub mod message;
+pub mod payload;
+pub mod signature;
+
+use serde::de::DeserializeOwned;
+
+/// A vector this implementation does not match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    /// Suite the vector belongs to
+    pub suite: &'static str,
+    /// Description of the vector
+    pub vector: String,
+    /// What did not match
+    pub reason: String,
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}: {}", self.suite, self.vector, self.reason)
+    }
+}
+
+/// A test vector with a description.
+pub trait Vector: DeserializeOwned {
+    /// What the vector exercises.
+    fn description(&self) -> &str;
+}
+
+/// Run every suite, returning the vectors that failed.
+pub fn run_all() -> Vec<Failure> {
+    [
+        hash::run(),
+        signature::run(),
+        message::run(),
+        payload::run(),
+        distribution::run(),
+        merkle::run(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Parse a suite's vector file.
+///
+/// # Panics
+///
+/// Panics if the file is not a JSON array of vectors; the files are
+/// compiled in, so this only happens if a committed fixture is broken.
+pub(crate) fn load<V: Vector>(suite: &str, json: &str) -> Vec<V> {
+    serde_json::from_str(json).unwrap_or_else(|e| panic!("{} vectors are malformed: {}", suite, e))
+}
+
+/// Check every vector of a suite.
+pub(crate) fn run_suite<V: Vector>(
+    suite: &'static str,
+    vectors: &[V],
+    check: impl Fn(&V) -> Result<(), String>,
+) -> Vec<Failure> {
+    vectors
+        .iter()
+        .filter_map(|vector| {
+            check(vector).err().map(|reason| Failure {
+                suite,
+                vector: vector.description().to_string(),
+                reason,
+            })
+        })
+        .collect()
+}
+
+/// Decode a hex field of a vector.
+pub(crate) fn decode_hex(field: &str, hex: &str) -> Result<Vec<u8>, String> {
+    hex::decode(hex).map_err(|e| format!("{} is not valid hex: {}", field, e))
+}
+
+/// Compare an expected and an actual value, describing any mismatch.
+pub(crate) fn expect_eq<T: PartialEq + std::fmt::Debug>(
+    what: &str,
+    expected: &T,
+    actual: &T,
+) -> Result<(), String> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} mismatch: expected {:?}, got {:?}",
+            what, expected, actual
+        ))
+    }
+}
@@ -0,0 +1,62 @@
+//! Settlement merkle vectors (§10.4).
+
+use nodalync_crypto::Hash;
+use nodalync_econ::{
+    compute_batch_id, compute_merkle_root, create_merkle_proof, verify_merkle_proof,
+};
+use nodalync_types::SettlementEntry;
+use serde::{Deserialize, Serialize};
+
+use crate::{expect_eq, Failure, Vector};
+
+/// Suite name.
+pub const SUITE: &str = "merkle";
+
+const VECTORS: &str = include_str!("../vectors/merkle.json");
+
+/// Settlement entries and the merkle root and batch ID over them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleVector {
+    /// What the vector exercises
+    pub description: String,
+    /// Settlement entries, in batch order
+    pub entries: Vec<SettlementEntry>,
+    /// Expected merkle root
+    pub root: Hash,
+    /// Expected batch ID
+    pub batch_id: Hash,
+}
+
+impl Vector for MerkleVector {
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// The merkle vectors.
+pub fn vectors() -> Vec<MerkleVector> {
+    crate::load(SUITE, VECTORS)
+}
+
+/// Check one vector, including a proof for every entry.
+pub fn check(vector: &MerkleVector) -> Result<(), String> {
+    expect_eq("root", &vector.root, &compute_merkle_root(&vector.entries))?;
+    expect_eq(
+        "batch ID",
+        &vector.batch_id,
+        &compute_batch_id(&vector.entries),
+    )?;
+    for (index, entry) in vector.entries.iter().enumerate() {
+        let proof = create_merkle_proof(&vector.entries, index)
+            .map_err(|e| format!("proof for entry {}: {}", index, e))?;
+        if !verify_merkle_proof(&vector.root, entry, &proof) {
+            return Err(format!("proof for entry {} does not verify", index));
+        }
+    }
+    Ok(())
+}
+
+/// Check every merkle vector.
+pub fn run() -> Vec<Failure> {
+    crate::run_suite(SUITE, &vectors(), check)
+}
@@ -0,0 +1,89 @@
+//! Signed message envelope vectors (§6.1).
+
+use nodalync_crypto::{peer_id_from_public_key, Hash, Signature, Timestamp};
+use nodalync_wire::{
+    create_message, decode_message, encode_message, message_hash, verify_message_signature,
+    MessageType,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{decode_hex, expect_eq, Failure, Vector};
+
+/// Suite name.
+pub const SUITE: &str = "message";
+
+const VECTORS: &str = include_str!("../vectors/message.json");
+
+/// A message's inputs, its derived ID, hash and signature, and its
+/// encoding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageVector {
+    /// What the vector exercises
+    pub description: String,
+    /// Sender's Ed25519 private key seed (hex)
+    pub private_key: String,
+    /// Message type code
+    pub message_type: u16,
+    /// Message timestamp (milliseconds since Unix epoch)
+    pub timestamp: Timestamp,
+    /// CBOR payload (hex)
+    pub payload: String,
+    /// Expected message ID
+    pub id: Hash,
+    /// Expected hash the signature covers
+    pub message_hash: Hash,
+    /// Expected signature
+    pub signature: Signature,
+    /// Expected encoded message (hex)
+    pub encoded: String,
+}
+
+impl Vector for MessageVector {
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// The message vectors.
+pub fn vectors() -> Vec<MessageVector> {
+    crate::load(SUITE, VECTORS)
+}
+
+/// Check one vector.
+pub fn check(vector: &MessageVector) -> Result<(), String> {
+    let private_key = crate::signature::private_key(&vector.private_key)?;
+    let public_key = private_key.public_key();
+    let message_type =
+        MessageType::from_u16(vector.message_type).map_err(|e| format!("message type: {}", e))?;
+    let payload = decode_hex("payload", &vector.payload)?;
+    let encoded = decode_hex("encoded", &vector.encoded)?;
+
+    let message = create_message(
+        message_type,
+        payload,
+        peer_id_from_public_key(&public_key),
+        vector.timestamp,
+        &private_key,
+    );
+    expect_eq("message ID", &vector.id, &message.id)?;
+    expect_eq(
+        "message hash",
+        &vector.message_hash,
+        &message_hash(&message),
+    )?;
+    expect_eq("signature", &vector.signature, &message.signature)?;
+    let actual = encode_message(&message).map_err(|e| format!("encoding failed: {}", e))?;
+    expect_eq("encoding", &vector.encoded, &hex::encode(actual))?;
+
+    let decoded = decode_message(&encoded).map_err(|e| format!("decoding failed: {}", e))?;
+    expect_eq("decoded message", &message, &decoded)?;
+    if !verify_message_signature(&decoded, &public_key) {
+        return Err("decoded message signature does not verify".to_string());
+    }
+    Ok(())
+}
+
+/// Check every message vector.
+pub fn run() -> Vec<Failure> {
+    crate::run_suite(SUITE, &vectors(), check)
+}
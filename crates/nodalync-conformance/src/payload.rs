@@ -0,0 +1,74 @@
+//! CBOR payload encoding vectors (§6).
+
+use std::fmt::Debug;
+
+use nodalync_wire::{
+    decode_payload, encode_payload, ChannelOpenPayload, PingPayload, PongPayload,
+    QueryErrorPayload, QueryRequestPayload, SearchPayload, SettleConfirmPayload,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::{decode_hex, expect_eq, Failure, Vector};
+
+/// Suite name.
+pub const SUITE: &str = "payload";
+
+const VECTORS: &str = include_str!("../vectors/payload.json");
+
+/// A payload and its CBOR encoding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PayloadVector {
+    /// What the vector exercises
+    pub description: String,
+    /// Payload type, the snake_case name of its message type
+    pub payload_type: String,
+    /// The payload in its JSON form
+    pub value: serde_json::Value,
+    /// Expected CBOR encoding (hex)
+    pub encoded: String,
+}
+
+impl Vector for PayloadVector {
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// The payload vectors.
+pub fn vectors() -> Vec<PayloadVector> {
+    crate::load(SUITE, VECTORS)
+}
+
+/// Check that `value` encodes to `encoded` and decodes back.
+fn roundtrip<T>(value: &serde_json::Value, encoded: &[u8]) -> Result<(), String>
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let payload: T = serde_json::from_value(value.clone()).map_err(|e| format!("value: {}", e))?;
+    let actual = encode_payload(&payload).map_err(|e| format!("encoding failed: {}", e))?;
+    expect_eq("encoding", &hex::encode(encoded), &hex::encode(actual))?;
+    let decoded: T = decode_payload(encoded).map_err(|e| format!("decoding failed: {}", e))?;
+    expect_eq("decoded payload", &payload, &decoded)
+}
+
+/// Check one vector.
+pub fn check(vector: &PayloadVector) -> Result<(), String> {
+    let encoded = decode_hex("encoded", &vector.encoded)?;
+    let value = &vector.value;
+    match vector.payload_type.as_str() {
+        "ping" => roundtrip::<PingPayload>(value, &encoded),
+        "pong" => roundtrip::<PongPayload>(value, &encoded),
+        "search" => roundtrip::<SearchPayload>(value, &encoded),
+        "query_request" => roundtrip::<QueryRequestPayload>(value, &encoded),
+        "query_error" => roundtrip::<QueryErrorPayload>(value, &encoded),
+        "channel_open" => roundtrip::<ChannelOpenPayload>(value, &encoded),
+        "settle_confirm" => roundtrip::<SettleConfirmPayload>(value, &encoded),
+        other => Err(format!("unknown payload type {:?}", other)),
+    }
+}
+
+/// Check every payload vector.
+pub fn run() -> Vec<Failure> {
+    crate::run_suite(SUITE, &vectors(), check)
+}
@@ -0,0 +1,82 @@
+//! Key, peer ID and signature vectors (§3.2, §3.3).
+
+use nodalync_crypto::{
+    peer_id_from_public_key, sign, verify, PeerId, PrivateKey, PublicKey, Signature,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{decode_hex, expect_eq, Failure, Vector};
+
+/// Suite name.
+pub const SUITE: &str = "signature";
+
+const VECTORS: &str = include_str!("../vectors/signature.json");
+
+/// A private key, what derives from it, and a signature made with it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureVector {
+    /// What the vector exercises
+    pub description: String,
+    /// Ed25519 private key seed (hex)
+    pub private_key: String,
+    /// Derived public key
+    pub public_key: PublicKey,
+    /// Derived peer ID, in its `ndl1...` string form
+    pub peer_id: PeerId,
+    /// Signed message (hex); the signature is Ed25519 over its SHA-256
+    /// hash
+    pub message: String,
+    /// Expected signature
+    pub signature: Signature,
+}
+
+impl Vector for SignatureVector {
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// Parse a hex private key seed.
+pub(crate) fn private_key(hex: &str) -> Result<PrivateKey, String> {
+    let bytes: [u8; 32] = decode_hex("private_key", hex)?
+        .try_into()
+        .map_err(|b: Vec<u8>| format!("private_key is {} bytes, expected 32", b.len()))?;
+    Ok(PrivateKey::from_bytes(bytes))
+}
+
+/// The signature vectors.
+pub fn vectors() -> Vec<SignatureVector> {
+    crate::load(SUITE, VECTORS)
+}
+
+/// Check one vector.
+pub fn check(vector: &SignatureVector) -> Result<(), String> {
+    let private_key = private_key(&vector.private_key)?;
+    let message = decode_hex("message", &vector.message)?;
+
+    let public_key = private_key.public_key();
+    expect_eq("public key", &vector.public_key, &public_key)?;
+    let peer_id = peer_id_from_public_key(&public_key);
+    expect_eq("peer ID", &vector.peer_id, &peer_id)?;
+
+    // Ed25519 signatures are deterministic
+    expect_eq(
+        "signature",
+        &vector.signature,
+        &sign(&private_key, &message),
+    )?;
+    if !verify(&public_key, &message, &vector.signature) {
+        return Err("signature does not verify".to_string());
+    }
+    let mut tampered = message;
+    tampered.push(0);
+    if verify(&public_key, &tampered, &vector.signature) {
+        return Err("signature verifies over a tampered message".to_string());
+    }
+    Ok(())
+}
+
+/// Check every signature vector.
+pub fn run() -> Vec<Failure> {
+    crate::run_suite(SUITE, &vectors(), check)
+}
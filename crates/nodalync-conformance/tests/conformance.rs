@@ -0,0 +1,71 @@
+//! Checks this implementation against the committed conformance vectors.
+
+use nodalync_conformance::{distribution, hash, merkle, message, payload, signature, Failure};
+
+fn assert_passes(failures: Vec<Failure>) {
+    let report: Vec<String> = failures.iter().map(ToString::to_string).collect();
+    assert!(
+        report.is_empty(),
+        "conformance failures:\n{}",
+        report.join("\n")
+    );
+}
+
+#[test]
+fn hash_vectors() {
+    assert!(!hash::vectors().is_empty());
+    assert_passes(hash::run());
+}
+
+#[test]
+fn signature_vectors() {
+    assert!(!signature::vectors().is_empty());
+    assert_passes(signature::run());
+}
+
+#[test]
+fn message_vectors() {
+    assert!(!message::vectors().is_empty());
+    assert_passes(message::run());
+}
+
+#[test]
+fn payload_vectors() {
+    assert!(!payload::vectors().is_empty());
+    assert_passes(payload::run());
+}
+
+#[test]
+fn distribution_vectors() {
+    assert!(!distribution::vectors().is_empty());
+    assert_passes(distribution::run());
+}
+
+#[test]
+fn merkle_vectors() {
+    assert!(!merkle::vectors().is_empty());
+    assert_passes(merkle::run());
+}
+
+/// A vector that disagrees with the implementation is reported, not
+/// silently passed.
+#[test]
+fn mismatches_are_reported() {
+    let mut vector = hash::vectors().remove(0);
+    vector.expected.0[0] ^= 0xff;
+    assert!(hash::check(&vector).unwrap_err().contains("mismatch"));
+
+    let mut vector = message::vectors().remove(0);
+    vector.timestamp += 1;
+    assert!(message::check(&vector).is_err());
+
+    let mut vector = distribution::vectors().remove(0);
+    vector.payment += 1;
+    assert!(distribution::check(&vector).is_err());
+
+    let mut vector = payload::vectors().remove(0);
+    vector.payload_type = "unknown".to_string();
+    assert!(payload::check(&vector)
+        .unwrap_err()
+        .contains("unknown payload type"));
+}
@@ -0,0 +1,1376 @@
+//! Multi-node in-process test harness.
+//!
+//! [`TestCluster`] runs several full `DefaultNodeOperations` instances in
+//! one process, connected through a [`MemoryBus`] instead of libp2p. Each
+//! node talks to the bus through a [`BusNetwork`], which implements the
+//! `Network` trait with real wire encoding and signing:
+//!
+//! - Requests are encoded with `encode_message`, handed to the target
+//!   node's `handle_inbound_request`, and the response is signed with the
+//!   target's key and decoded again, exactly as over a socket.
+//! - DHT records are stored encoded in a map shared by all nodes.
+//! - Broadcasts are queued as `BroadcastReceived` events on every other
+//!   node and handled when [`TestCluster::deliver_events`] is called.
+//!
+//! Requests are delivered synchronously, so a node cannot send a request
+//! to itself while handling one.
+//!
+//! ```ignore
+//! let cluster = TestCluster::new(2);
+//! let hash = cluster.publish(0, b"hello", "Greeting", 0).await?;
+//! let response = cluster.query(1, &hash, 0).await?;
+//! assert_eq!(response.content, b"hello");
+//! ```
+
+use async_trait::async_trait;
+use libp2p::Multiaddr;
+use nodalync_crypto::{generate_identity, peer_id_from_public_key, Hash, PeerId, PrivateKey};
+use nodalync_net::{
+    Network, NetworkConfig, NetworkError, NetworkEvent, NetworkResult, RelayConfig,
+};
+use nodalync_ops::{
+    current_timestamp, DefaultNodeOperations, OpsResult, PreviewResponse, QueryResponse,
+};
+use nodalync_store::{NodeState, NodeStateConfig, PeerInfo, PeerStore};
+use nodalync_types::{Amount, Channel, Metadata, Visibility};
+use nodalync_wire::{
+    create_message, decode_message, decode_payload, encode_message, encode_payload, storefront_key,
+    AnnouncePayload, AvailabilityCheckPayload, AvailabilityResultPayload, ChannelClosePayload,
+    ChannelOpenPayload, ChannelSyncPayload, ChannelSyncResponsePayload, ChunkRequestPayload,
+    ChunkResponsePayload, FraudProofPayload, GroupRequestPayload, GroupResponsePayload,
+    InvoiceAckPayload, InvoicePayPayload, InvoicePayload, InvoiceRequestPayload, Message,
+    MessageType, PeerInfoPayload, PingPayload, PongPayload, PreviewRequestPayload,
+    PreviewResponsePayload, QueryErrorPayload, QueryRequestPayload, QueryResponsePayload,
+    RelayQueryPayload, RelayResponsePayload, ReportPayload, RevocationPayload, SearchPayload,
+    SearchResponsePayload, SettleConfirmPayload, SnapshotRequestPayload, SnapshotResponsePayload,
+    Storefront, SyncPullPayload, SyncPullResponsePayload, SyncPushAckPayload, SyncPushPayload,
+    TombstonePayload, UsageReportAckPayload, UsageReportPayload, VersionRequestPayload,
+    VersionResponsePayload,
+};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use tempfile::TempDir;
+use tokio::sync::{Mutex as AsyncMutex, MutexGuard};
+
+use crate::MockSettlement;
+
+/// Contract balance each cluster node starts with (1000 HBAR in tinybars).
+const INITIAL_BALANCE: u64 = 1000_0000_0000;
+
+/// A node attached to the bus.
+struct Endpoint {
+    nodalync_peer_id: PeerId,
+    private_key: PrivateKey,
+    ops: Weak<AsyncMutex<DefaultNodeOperations>>,
+    events: VecDeque<NetworkEvent>,
+}
+
+#[derive(Default)]
+struct MemoryBusInner {
+    /// Encoded DHT records, shared by all nodes.
+    dht: HashMap<Hash, Vec<u8>>,
+    /// Attached nodes by libp2p peer ID.
+    endpoints: HashMap<libp2p::PeerId, Endpoint>,
+    /// Peer ID mappings: Nodalync -> libp2p.
+    nodalync_to_libp2p: HashMap<PeerId, libp2p::PeerId>,
+    /// Peer ID mappings: libp2p -> Nodalync.
+    libp2p_to_nodalync: HashMap<libp2p::PeerId, PeerId>,
+}
+
+/// In-memory message bus connecting the nodes of a [`TestCluster`].
+///
+/// Cheap to clone; all clones share the same state.
+#[derive(Clone, Default)]
+pub struct MemoryBus {
+    inner: Arc<Mutex<MemoryBusInner>>,
+}
+
+impl MemoryBus {
+    /// Create an empty bus.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a network handle for a new node.
+    pub fn network(&self, nodalync_peer_id: PeerId, private_key: PrivateKey) -> BusNetwork {
+        let local_peer_id = libp2p::PeerId::random();
+        let mut inner = self.inner.lock().unwrap();
+        inner
+            .nodalync_to_libp2p
+            .insert(nodalync_peer_id, local_peer_id);
+        inner
+            .libp2p_to_nodalync
+            .insert(local_peer_id, nodalync_peer_id);
+        BusNetwork {
+            bus: self.clone(),
+            local_peer_id,
+            nodalync_peer_id,
+            private_key,
+            topic: NetworkConfig::default().gossipsub_topic,
+            relay: Arc::new(Mutex::new(RelayConfig::default())),
+            clock_offset: Arc::new(AtomicI64::new(0)),
+        }
+    }
+
+    /// Attach a node's operations so it can receive requests and events.
+    pub fn attach(&self, network: &BusNetwork, ops: &Arc<AsyncMutex<DefaultNodeOperations>>) {
+        self.inner.lock().unwrap().endpoints.insert(
+            network.local_peer_id,
+            Endpoint {
+                nodalync_peer_id: network.nodalync_peer_id,
+                private_key: network.private_key.clone(),
+                ops: Arc::downgrade(ops),
+                events: VecDeque::new(),
+            },
+        );
+    }
+
+    /// Number of events queued for a node.
+    pub fn pending_events(&self, peer: &libp2p::PeerId) -> usize {
+        self.inner
+            .lock()
+            .unwrap()
+            .endpoints
+            .get(peer)
+            .map_or(0, |e| e.events.len())
+    }
+
+    /// Number of records in the shared DHT.
+    pub fn dht_len(&self) -> usize {
+        self.inner.lock().unwrap().dht.len()
+    }
+
+    fn push_event(&self, peer: &libp2p::PeerId, event: NetworkEvent) {
+        if let Some(endpoint) = self.inner.lock().unwrap().endpoints.get_mut(peer) {
+            endpoint.events.push_back(event);
+        }
+    }
+
+    fn pop_event(&self, peer: &libp2p::PeerId) -> Option<NetworkEvent> {
+        self.inner
+            .lock()
+            .unwrap()
+            .endpoints
+            .get_mut(peer)
+            .and_then(|e| e.events.pop_front())
+    }
+}
+
+/// A node's view of the [`MemoryBus`], implementing `Network`.
+#[derive(Clone)]
+pub struct BusNetwork {
+    bus: MemoryBus,
+    local_peer_id: libp2p::PeerId,
+    nodalync_peer_id: PeerId,
+    private_key: PrivateKey,
+    topic: String,
+    relay: Arc<Mutex<RelayConfig>>,
+    clock_offset: Arc<AtomicI64>,
+}
+
+impl BusNetwork {
+    /// Set the relayed query configuration the node sees.
+    pub fn set_relay_config(&self, config: RelayConfig) {
+        *self.relay.lock().unwrap() = config;
+    }
+
+    fn create_signed_message(&self, message_type: MessageType, payload: Vec<u8>) -> Message {
+        create_message(
+            message_type,
+            payload,
+            self.nodalync_peer_id,
+            current_timestamp().saturating_add_signed(self.clock_offset.load(Ordering::Relaxed)),
+            &self.private_key,
+        )
+    }
+
+    async fn send_typed<T: serde::Serialize>(
+        &self,
+        peer: libp2p::PeerId,
+        message_type: MessageType,
+        payload: &T,
+    ) -> NetworkResult<Message> {
+        let payload = encode_payload(payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(message_type, payload);
+        self.send(peer, message).await
+    }
+}
+
+/// Check a response's type and decode its payload.
+fn expect_response<T: serde::de::DeserializeOwned>(
+    response: Message,
+    expected: MessageType,
+) -> NetworkResult<T> {
+    if response.message_type != expected {
+        return Err(NetworkError::InvalidResponseType {
+            expected: format!("{:?}", expected),
+            got: format!("{:?}", response.message_type),
+        });
+    }
+    decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))
+}
+
+#[async_trait]
+impl Network for BusNetwork {
+    // =========================================================================
+    // DHT Operations
+    // =========================================================================
+
+    async fn dht_announce(&self, hash: Hash, payload: AnnouncePayload) -> NetworkResult<()> {
+        let value = encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        self.bus.inner.lock().unwrap().dht.insert(hash, value);
+        Ok(())
+    }
+
+    async fn dht_get(&self, hash: &Hash) -> NetworkResult<Option<AnnouncePayload>> {
+        let value = self.bus.inner.lock().unwrap().dht.get(hash).cloned();
+        value
+            .map(|data| decode_payload(&data).map_err(|e| NetworkError::Decoding(e.to_string())))
+            .transpose()
+    }
+
+    async fn dht_remove(&self, hash: &Hash) -> NetworkResult<()> {
+        self.bus.inner.lock().unwrap().dht.remove(hash);
+        Ok(())
+    }
+
+    async fn dht_put_storefront(&self, storefront: Storefront) -> NetworkResult<()> {
+        let key = storefront_key(&storefront.publisher);
+        let value =
+            encode_payload(&storefront).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        self.bus.inner.lock().unwrap().dht.insert(key, value);
+        Ok(())
+    }
+
+    async fn dht_get_storefront(&self, publisher: &PeerId) -> NetworkResult<Option<Storefront>> {
+        let value = self
+            .bus
+            .inner
+            .lock()
+            .unwrap()
+            .dht
+            .get(&storefront_key(publisher))
+            .cloned();
+        value
+            .map(|data| decode_payload(&data).map_err(|e| NetworkError::Decoding(e.to_string())))
+            .transpose()
+    }
+
+    // =========================================================================
+    // Messaging
+    // =========================================================================
+
+    async fn send(&self, peer: libp2p::PeerId, message: Message) -> NetworkResult<Message> {
+        if peer == self.local_peer_id {
+            return Err(NetworkError::PeerNotFound(
+                "cannot send a request to the local node".to_string(),
+            ));
+        }
+        let data = encode_message(&message).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+
+        let (ops, responder, responder_key) = {
+            let inner = self.bus.inner.lock().unwrap();
+            let endpoint = inner
+                .endpoints
+                .get(&peer)
+                .ok_or_else(|| NetworkError::PeerNotFound(peer.to_string()))?;
+            (
+                endpoint.ops.upgrade(),
+                endpoint.nodalync_peer_id,
+                endpoint.private_key.clone(),
+            )
+        };
+        let ops = ops.ok_or_else(|| NetworkError::PeerNotFound(peer.to_string()))?;
+
+        // Handled on a task of its own, as a real node would, so a request
+        // forwarded through several nodes doesn't nest on one task's stack
+        let requester = self.local_peer_id;
+        let result = tokio::spawn(async move {
+            ops.lock()
+                .await
+                .handle_inbound_request(&requester, &data)
+                .await
+        })
+        .await
+        .map_err(|e| NetworkError::Timeout(format!("request to {} failed: {}", peer, e)))?;
+
+        match result {
+            Ok(Some((message_type, payload))) => {
+                let response = create_message(
+                    message_type,
+                    payload,
+                    responder,
+                    current_timestamp(),
+                    &responder_key,
+                );
+                let bytes =
+                    encode_message(&response).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+                decode_message(&bytes).map_err(|e| NetworkError::Decoding(e.to_string()))
+            }
+            // A real node sends no response, so the requester times out
+            Ok(None) => Err(NetworkError::Timeout(format!("no response from {}", peer))),
+            Err(e) => Err(NetworkError::Timeout(format!(
+                "request to {} failed: {}",
+                peer, e
+            ))),
+        }
+    }
+
+    async fn broadcast(&self, message: Message) -> NetworkResult<()> {
+        let data = encode_message(&message).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let peers: Vec<libp2p::PeerId> = self
+            .bus
+            .inner
+            .lock()
+            .unwrap()
+            .endpoints
+            .keys()
+            .filter(|p| **p != self.local_peer_id)
+            .copied()
+            .collect();
+        for peer in peers {
+            self.bus.push_event(
+                &peer,
+                NetworkEvent::BroadcastReceived {
+                    topic: self.topic.clone(),
+                    data: data.clone(),
+                },
+            );
+        }
+        Ok(())
+    }
+
+    // =========================================================================
+    // Typed Message Helpers
+    // =========================================================================
+
+    async fn send_preview_request(
+        &self,
+        peer: libp2p::PeerId,
+        request: PreviewRequestPayload,
+    ) -> NetworkResult<PreviewResponsePayload> {
+        let response = self
+            .send_typed(peer, MessageType::PreviewRequest, &request)
+            .await?;
+        expect_response(response, MessageType::PreviewResponse)
+    }
+
+    async fn send_query(
+        &self,
+        peer: libp2p::PeerId,
+        request: QueryRequestPayload,
+    ) -> NetworkResult<QueryResponsePayload> {
+        let response = self
+            .send_typed(peer, MessageType::QueryRequest, &request)
+            .await?;
+        if response.message_type != MessageType::QueryError {
+            return expect_response(response, MessageType::QueryResponse);
+        }
+
+        let mut error: QueryErrorPayload =
+            decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))?;
+        if error.error_code == nodalync_types::ErrorCode::ChannelNotFound
+            && (error.required_channel_peer_id.is_some()
+                || error.required_channel_libp2p_peer.is_some())
+        {
+            return Err(NetworkError::ChannelRequired {
+                nodalync_peer_id: error.required_channel_peer_id.map(|p| p.0),
+                libp2p_peer_id: error.required_channel_libp2p_peer,
+            });
+        }
+        if let (nodalync_types::ErrorCode::ChallengeRequired, Some(challenge)) =
+            (error.error_code, error.challenge.take())
+        {
+            return Err(NetworkError::ChallengeRequired { challenge });
+        }
+        Err(error.into())
+    }
+
+    async fn send_search(
+        &self,
+        peer: libp2p::PeerId,
+        request: SearchPayload,
+    ) -> NetworkResult<SearchResponsePayload> {
+        let response = self.send_typed(peer, MessageType::Search, &request).await?;
+        expect_response(response, MessageType::SearchResponse)
+    }
+
+    async fn send_version_request(
+        &self,
+        peer: libp2p::PeerId,
+        request: VersionRequestPayload,
+    ) -> NetworkResult<VersionResponsePayload> {
+        let response = self
+            .send_typed(peer, MessageType::VersionRequest, &request)
+            .await?;
+        expect_response(response, MessageType::VersionResponse)
+    }
+
+    async fn send_channel_open(
+        &self,
+        peer: libp2p::PeerId,
+        payload: ChannelOpenPayload,
+    ) -> NetworkResult<Message> {
+        self.send_typed(peer, MessageType::ChannelOpen, &payload)
+            .await
+    }
+
+    async fn send_channel_close(
+        &self,
+        peer: libp2p::PeerId,
+        payload: ChannelClosePayload,
+    ) -> NetworkResult<Message> {
+        self.send_typed(peer, MessageType::ChannelClose, &payload)
+            .await
+    }
+
+    async fn send_channel_sync(
+        &self,
+        peer: libp2p::PeerId,
+        payload: ChannelSyncPayload,
+    ) -> NetworkResult<ChannelSyncResponsePayload> {
+        let response = self
+            .send_typed(peer, MessageType::ChannelSync, &payload)
+            .await?;
+        expect_response(response, MessageType::ChannelSyncResponse)
+    }
+
+    async fn send_invoice(
+        &self,
+        peer: libp2p::PeerId,
+        payload: InvoicePayload,
+    ) -> NetworkResult<InvoiceAckPayload> {
+        let response = self
+            .send_typed(peer, MessageType::Invoice, &payload)
+            .await?;
+        expect_response(response, MessageType::InvoiceAck)
+    }
+
+    async fn request_invoice(
+        &self,
+        peer: libp2p::PeerId,
+        payload: InvoiceRequestPayload,
+    ) -> NetworkResult<Message> {
+        self.send_typed(peer, MessageType::InvoiceRequest, &payload)
+            .await
+    }
+
+    async fn send_invoice_payment(
+        &self,
+        peer: libp2p::PeerId,
+        payload: InvoicePayPayload,
+    ) -> NetworkResult<InvoiceAckPayload> {
+        let response = self
+            .send_typed(peer, MessageType::InvoicePay, &payload)
+            .await?;
+        expect_response(response, MessageType::InvoiceAck)
+    }
+
+    async fn send_usage_report(
+        &self,
+        peer: libp2p::PeerId,
+        payload: UsageReportPayload,
+    ) -> NetworkResult<UsageReportAckPayload> {
+        let response = self
+            .send_typed(peer, MessageType::UsageReport, &payload)
+            .await?;
+        expect_response(response, MessageType::UsageReportAck)
+    }
+
+    async fn send_relay_query(
+        &self,
+        peer: libp2p::PeerId,
+        payload: RelayQueryPayload,
+    ) -> NetworkResult<RelayResponsePayload> {
+        let response = self
+            .send_typed(peer, MessageType::RelayQuery, &payload)
+            .await?;
+        if response.message_type != MessageType::QueryError {
+            return expect_response(response, MessageType::RelayResponse);
+        }
+
+        let error: QueryErrorPayload =
+            decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))?;
+        Err(error.into())
+    }
+
+    async fn request_chunk(
+        &self,
+        peer: libp2p::PeerId,
+        payload: ChunkRequestPayload,
+    ) -> NetworkResult<ChunkResponsePayload> {
+        let response = self
+            .send_typed(peer, MessageType::ChunkRequest, &payload)
+            .await?;
+        if response.message_type != MessageType::QueryError {
+            return expect_response(response, MessageType::ChunkResponse);
+        }
+
+        let error: QueryErrorPayload =
+            decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))?;
+        Err(error.into())
+    }
+
+    async fn send_ping(
+        &self,
+        peer: libp2p::PeerId,
+        payload: PingPayload,
+    ) -> NetworkResult<PongPayload> {
+        let response = self.send_typed(peer, MessageType::Ping, &payload).await?;
+        expect_response(response, MessageType::Pong)
+    }
+
+    async fn send_peer_info(
+        &self,
+        peer: libp2p::PeerId,
+        payload: PeerInfoPayload,
+    ) -> NetworkResult<PeerInfoPayload> {
+        let response = self
+            .send_typed(peer, MessageType::PeerInfo, &payload)
+            .await?;
+        expect_response(response, MessageType::PeerInfo)
+    }
+
+    async fn request_group(
+        &self,
+        peer: libp2p::PeerId,
+        payload: GroupRequestPayload,
+    ) -> NetworkResult<GroupResponsePayload> {
+        let response = self
+            .send_typed(peer, MessageType::GroupRequest, &payload)
+            .await?;
+        expect_response(response, MessageType::GroupResponse)
+    }
+
+    async fn request_snapshot(
+        &self,
+        peer: libp2p::PeerId,
+        payload: SnapshotRequestPayload,
+    ) -> NetworkResult<SnapshotResponsePayload> {
+        let response = self
+            .send_typed(peer, MessageType::SnapshotRequest, &payload)
+            .await?;
+        expect_response(response, MessageType::SnapshotResponse)
+    }
+
+    async fn push_sync_bundle(
+        &self,
+        peer: libp2p::PeerId,
+        payload: SyncPushPayload,
+    ) -> NetworkResult<SyncPushAckPayload> {
+        let response = self
+            .send_typed(peer, MessageType::SyncPush, &payload)
+            .await?;
+        expect_response(response, MessageType::SyncPushAck)
+    }
+
+    async fn pull_sync_bundle(
+        &self,
+        peer: libp2p::PeerId,
+        payload: SyncPullPayload,
+    ) -> NetworkResult<SyncPullResponsePayload> {
+        let response = self
+            .send_typed(peer, MessageType::SyncPull, &payload)
+            .await?;
+        expect_response(response, MessageType::SyncPullResponse)
+    }
+
+    async fn send_availability_check(
+        &self,
+        peer: libp2p::PeerId,
+        payload: AvailabilityCheckPayload,
+    ) -> NetworkResult<AvailabilityResultPayload> {
+        let response = self
+            .send_typed(peer, MessageType::AvailabilityCheck, &payload)
+            .await?;
+        expect_response(response, MessageType::AvailabilityResult)
+    }
+
+    async fn broadcast_settlement_confirm(
+        &self,
+        payload: SettleConfirmPayload,
+    ) -> NetworkResult<()> {
+        let payload =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::SettleConfirm, payload);
+        self.broadcast(message).await
+    }
+
+    async fn broadcast_announce(&self, payload: AnnouncePayload) -> NetworkResult<()> {
+        let payload =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::Announce, payload);
+        self.broadcast(message).await
+    }
+
+    async fn broadcast_tag_announce(
+        &self,
+        _tag: &str,
+        _payload: AnnouncePayload,
+    ) -> NetworkResult<()> {
+        // The bus has no topics: the plain announcement already reached every node
+        Ok(())
+    }
+
+    async fn broadcast_tombstone(&self, payload: TombstonePayload) -> NetworkResult<()> {
+        let payload =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::Tombstone, payload);
+        self.broadcast(message).await
+    }
+
+    async fn broadcast_report(&self, payload: ReportPayload) -> NetworkResult<()> {
+        let payload =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::Report, payload);
+        self.broadcast(message).await
+    }
+
+    async fn broadcast_revocation(&self, payload: RevocationPayload) -> NetworkResult<()> {
+        let payload =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::Revocation, payload);
+        self.broadcast(message).await
+    }
+
+    async fn broadcast_fraud_proof(&self, payload: FraudProofPayload) -> NetworkResult<()> {
+        let payload =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::FraudProof, payload);
+        self.broadcast(message).await
+    }
+
+    // =========================================================================
+    // Peer Management
+    // =========================================================================
+
+    fn connected_peers(&self) -> Vec<libp2p::PeerId> {
+        self.bus
+            .inner
+            .lock()
+            .unwrap()
+            .endpoints
+            .keys()
+            .filter(|p| **p != self.local_peer_id)
+            .copied()
+            .collect()
+    }
+
+    fn listen_addresses(&self) -> Vec<Multiaddr> {
+        Vec::new()
+    }
+
+    async fn dial(&self, _addr: Multiaddr) -> NetworkResult<()> {
+        Ok(())
+    }
+
+    async fn dial_peer(&self, _peer: libp2p::PeerId) -> NetworkResult<()> {
+        Ok(())
+    }
+
+    // =========================================================================
+    // Events
+    // =========================================================================
+
+    async fn next_event(&self) -> NetworkResult<NetworkEvent> {
+        self.bus
+            .pop_event(&self.local_peer_id)
+            .ok_or(NetworkError::ChannelClosed)
+    }
+
+    async fn send_response(
+        &self,
+        _request_id: libp2p::request_response::InboundRequestId,
+        _data: Vec<u8>,
+    ) -> NetworkResult<()> {
+        // Requests are answered synchronously in `send`
+        Ok(())
+    }
+
+    async fn send_signed_response(
+        &self,
+        _request_id: libp2p::request_response::InboundRequestId,
+        _message_type: MessageType,
+        _payload: Vec<u8>,
+    ) -> NetworkResult<()> {
+        Ok(())
+    }
+
+    // =========================================================================
+    // Utility
+    // =========================================================================
+
+    fn local_peer_id(&self) -> libp2p::PeerId {
+        self.local_peer_id
+    }
+
+    fn nodalync_peer_id(&self, libp2p_peer: &libp2p::PeerId) -> Option<PeerId> {
+        self.bus
+            .inner
+            .lock()
+            .unwrap()
+            .libp2p_to_nodalync
+            .get(libp2p_peer)
+            .copied()
+    }
+
+    fn libp2p_peer_id(&self, nodalync_peer: &PeerId) -> Option<libp2p::PeerId> {
+        self.bus
+            .inner
+            .lock()
+            .unwrap()
+            .nodalync_to_libp2p
+            .get(nodalync_peer)
+            .copied()
+    }
+
+    fn register_peer_mapping(&self, libp2p_peer: libp2p::PeerId, nodalync_peer: PeerId) {
+        let mut inner = self.bus.inner.lock().unwrap();
+        inner.nodalync_to_libp2p.insert(nodalync_peer, libp2p_peer);
+        inner.libp2p_to_nodalync.insert(libp2p_peer, nodalync_peer);
+    }
+
+    fn relay_config(&self) -> RelayConfig {
+        self.relay.lock().unwrap().clone()
+    }
+
+    fn set_clock_offset(&self, offset_ms: i64) {
+        self.clock_offset.store(offset_ms, Ordering::Relaxed);
+    }
+
+    fn set_broadcast_backpressure(&self, _engaged: bool) {
+        // The bus delivers every broadcast; nodes drain them as they run
+    }
+}
+
+/// One node of a [`TestCluster`].
+pub struct ClusterNode {
+    /// Nodalync peer ID.
+    pub peer_id: PeerId,
+    /// libp2p peer ID on the bus.
+    pub libp2p_peer_id: libp2p::PeerId,
+    /// The node's operations.
+    pub ops: Arc<AsyncMutex<DefaultNodeOperations>>,
+    /// The node's network handle.
+    pub network: BusNetwork,
+    /// The node's settlement mock (for assertions).
+    pub settlement: MockSettlement,
+    _temp_dir: TempDir,
+}
+
+impl ClusterNode {
+    /// Lock the node's operations.
+    pub async fn ops(&self) -> MutexGuard<'_, DefaultNodeOperations> {
+        self.ops.lock().await
+    }
+
+    /// The node's data directory.
+    pub fn data_dir(&self) -> &std::path::Path {
+        self._temp_dir.path()
+    }
+}
+
+/// N full nodes wired through a [`MemoryBus`].
+///
+/// Every node has its own storage, identity, and `MockSettlement` (funded
+/// with 1000 HBAR), and knows every other node's public key, so message
+/// signatures are verified as on a real network.
+pub struct TestCluster {
+    bus: MemoryBus,
+    nodes: Vec<ClusterNode>,
+}
+
+impl TestCluster {
+    /// Create a cluster of `size` nodes.
+    pub fn new(size: usize) -> Self {
+        let bus = MemoryBus::new();
+        let mut nodes = Vec::with_capacity(size);
+        let mut keys = Vec::with_capacity(size);
+
+        for _ in 0..size {
+            let temp_dir = TempDir::new().unwrap();
+            let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+            let (private_key, public_key) = generate_identity();
+            let peer_id = peer_id_from_public_key(&public_key);
+
+            let network = bus.network(peer_id, private_key.clone());
+            let settlement = MockSettlement::new().with_balance(INITIAL_BALANCE);
+            let mut ops = DefaultNodeOperations::with_defaults_network_and_settlement(
+                state,
+                peer_id,
+                Arc::new(network.clone()),
+                Arc::new(settlement.clone()),
+            );
+            ops.set_private_key(private_key);
+            let ops = Arc::new(AsyncMutex::new(ops));
+            bus.attach(&network, &ops);
+
+            keys.push((peer_id, public_key));
+            nodes.push(ClusterNode {
+                peer_id,
+                libp2p_peer_id: network.local_peer_id,
+                ops,
+                network,
+                settlement,
+                _temp_dir: temp_dir,
+            });
+        }
+
+        // Every node knows every other node's key
+        let now = current_timestamp();
+        for node in &nodes {
+            let mut ops = node.ops.try_lock().expect("new cluster node is unlocked");
+            for (peer_id, public_key) in keys.iter().filter(|(p, _)| *p != node.peer_id) {
+                let info = PeerInfo::new(*peer_id, *public_key, Vec::new(), now);
+                ops.state_mut().peers.upsert(&info).unwrap();
+            }
+        }
+
+        Self { bus, nodes }
+    }
+
+    /// Number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether the cluster has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Get a node by index.
+    pub fn node(&self, index: usize) -> &ClusterNode {
+        &self.nodes[index]
+    }
+
+    /// Get all nodes.
+    pub fn nodes(&self) -> &[ClusterNode] {
+        &self.nodes
+    }
+
+    /// Get the bus connecting the nodes.
+    pub fn bus(&self) -> &MemoryBus {
+        &self.bus
+    }
+
+    // =========================================================================
+    // Protocol Helpers
+    // =========================================================================
+
+    /// Create and publish shared content on a node.
+    pub async fn publish(
+        &self,
+        index: usize,
+        content: &[u8],
+        title: &str,
+        price: Amount,
+    ) -> OpsResult<Hash> {
+        let ops = self.node(index).ops().await;
+        let hash = ops.create_content(content, Metadata::new(title, content.len() as u64))?;
+        ops.publish_content(&hash, Visibility::Shared, price)
+            .await?;
+        Ok(hash)
+    }
+
+    /// Preview content from a node.
+    pub async fn preview(&self, index: usize, hash: &Hash) -> OpsResult<PreviewResponse> {
+        self.node(index).ops().await.preview_content(hash).await
+    }
+
+    /// Query content from a node, paying up to `payment`.
+    pub async fn query(
+        &self,
+        index: usize,
+        hash: &Hash,
+        payment: Amount,
+    ) -> OpsResult<QueryResponse> {
+        self.node(index)
+            .ops()
+            .await
+            .query_content(hash, payment, None)
+            .await
+    }
+
+    /// Open a payment channel from one node to another.
+    pub async fn open_channel(
+        &self,
+        from: usize,
+        to: usize,
+        deposit: Amount,
+    ) -> OpsResult<Channel> {
+        let peer = self.node(to).peer_id;
+        self.node(from)
+            .ops()
+            .await
+            .open_payment_channel(&peer, deposit)
+            .await
+    }
+
+    /// Deliver queued events (broadcasts) to every node until none remain.
+    ///
+    /// Returns the number of events handled.
+    pub async fn deliver_events(&self) -> usize {
+        let mut handled = 0;
+        loop {
+            let mut progressed = false;
+            for node in &self.nodes {
+                while let Ok(event) = node.network.next_event().await {
+                    // Handler errors are logged by a real node and ignored
+                    let _ = node.ops().await.handle_network_event(event).await;
+                    handled += 1;
+                    progressed = true;
+                }
+            }
+            if !progressed {
+                return handled;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_ops::{OpsError, ScrubOutcome};
+    use nodalync_store::{
+        AccessLogStore, AccessRequester, ChannelStore, ContentStore, ManifestStore,
+    };
+    use nodalync_types::{ChannelState, Collection, CollectionItem, CHUNK_SIZE};
+
+    #[tokio::test]
+    async fn test_publish_and_query_free_content() {
+        let cluster = TestCluster::new(3);
+        let hash = cluster
+            .publish(0, b"shared knowledge", "Shared", 0)
+            .await
+            .unwrap();
+        assert_eq!(cluster.bus().dht_len(), 1);
+
+        let preview = cluster.preview(2, &hash).await.unwrap();
+        assert_eq!(preview.manifest.metadata.title, "Shared");
+
+        let response = cluster.query(1, &hash, 0).await.unwrap();
+        assert_eq!(response.content, b"shared knowledge");
+        assert!(cluster.node(1).ops().await.is_content_cached(&hash));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_latest_version_as_delta() {
+        let cluster = TestCluster::new(2);
+        let v1 = b"Chapter one. ".repeat(100);
+        let hash1 = cluster.publish(0, &v1, "Book", 0).await.unwrap();
+        cluster.query(1, &hash1, 0).await.unwrap();
+
+        let mut v2 = v1.clone();
+        v2.extend_from_slice(b"Chapter two.");
+        let hash2 = {
+            let ops = cluster.node(0).ops().await;
+            let hash2 = ops
+                .update_content(&hash1, &v2, Metadata::new("Book", v2.len() as u64))
+                .unwrap();
+            ops.publish_content(&hash2, Visibility::Shared, 0)
+                .await
+                .unwrap();
+            hash2
+        };
+
+        let ops = cluster.node(1).ops().await;
+        let response = ops.fetch_latest_version(&hash1).await.unwrap().unwrap();
+        assert_eq!(response.manifest.hash, hash2);
+        assert_eq!(response.content, v2);
+        assert!(ops.is_content_cached(&hash2));
+
+        // Already up to date
+        assert!(ops.fetch_latest_version(&hash2).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_repair_version_chain_from_owner() {
+        let cluster = TestCluster::new(2);
+        let hash1 = cluster
+            .publish(0, b"Edition one", "Almanac", 0)
+            .await
+            .unwrap();
+        let (hash2, hash3) = {
+            let ops = cluster.node(0).ops().await;
+            let hash2 = ops
+                .update_content(&hash1, b"Edition two", Metadata::new("Almanac", 11))
+                .unwrap();
+            ops.publish_content(&hash2, Visibility::Shared, 0)
+                .await
+                .unwrap();
+            let hash3 = ops
+                .update_content(&hash2, b"Edition three", Metadata::new("Almanac", 13))
+                .unwrap();
+            ops.publish_content(&hash3, Visibility::Shared, 0)
+                .await
+                .unwrap();
+            (hash2, hash3)
+        };
+
+        // Node 1 holds editions one and three, but not two
+        cluster.query(1, &hash1, 0).await.unwrap();
+        cluster.query(1, &hash3, 0).await.unwrap();
+        let ops = cluster.node(1).ops().await;
+        let report = ops.verify_version_chain(&hash3).unwrap();
+        assert_eq!(report.gaps.len(), 1);
+        assert_eq!(report.gaps[0].missing, hash2);
+
+        let repair = ops.repair_version_chain(&hash3).await.unwrap();
+        assert_eq!(repair.fetched, vec![hash2]);
+        assert!(repair.report.is_intact());
+        assert_eq!(repair.report.versions, 3);
+        assert_eq!(ops.get_content_versions(&hash1).unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_scrub_refetches_from_owner() {
+        let cluster = TestCluster::new(2);
+        let content = b"mirrored knowledge";
+        let hash = cluster.publish(0, content, "Mirrored", 0).await.unwrap();
+
+        // Node 1 holds a copy of node 0's content that later rots on disk
+        let manifest = cluster.node(0).ops().await.get_content_manifest(&hash);
+        {
+            let mut ops = cluster.node(1).ops().await;
+            let state = ops.state_mut();
+            state.manifests.store(&manifest.unwrap().unwrap()).unwrap();
+            state.content.store_verified(&hash, content).unwrap();
+        }
+        let hex = hash.to_string();
+        let path = cluster
+            .node(1)
+            .data_dir()
+            .join("content")
+            .join(&hex[..4])
+            .join(&hex);
+        std::fs::write(path, b"rotten").unwrap();
+
+        let ops = cluster.node(1).ops().await;
+        let report = ops.scrub_content().await.unwrap();
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].outcome, ScrubOutcome::Refetched);
+        assert_eq!(ops.state().content.load(&hash).unwrap().unwrap(), content);
+        assert!(ops.state().content.quarantined().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_chunked_download_skips_corrupt_provider() {
+        let cluster = TestCluster::new(3);
+        let content: Vec<u8> = (0..CHUNK_SIZE * 5 / 2).map(|i| (i % 251) as u8).collect();
+        let hash = cluster.publish(0, &content, "Dataset", 0).await.unwrap();
+
+        // Node 1 also serves the content, but its copy is corrupt in the
+        // second chunk
+        let manifest = cluster.node(0).ops().await.get_content_manifest(&hash);
+        {
+            let mut ops = cluster.node(1).ops().await;
+            let state = ops.state_mut();
+            state.manifests.store(&manifest.unwrap().unwrap()).unwrap();
+            state.content.store_verified(&hash, &content).unwrap();
+        }
+        let mut corrupt = content.clone();
+        corrupt[CHUNK_SIZE as usize + 1] ^= 0xFF;
+        let hex = hash.to_string();
+        let path = cluster
+            .node(1)
+            .data_dir()
+            .join("content")
+            .join(&hex[..4])
+            .join(&hex);
+        std::fs::write(path, corrupt).unwrap();
+
+        let response = cluster.query(2, &hash, 0).await.unwrap();
+        assert_eq!(response.content, content);
+        assert!(cluster.node(2).ops().await.is_content_cached(&hash));
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_announcements_are_delivered() {
+        let cluster = TestCluster::new(3);
+        let hash = cluster
+            .publish(0, b"announced", "Announced", 0)
+            .await
+            .unwrap();
+
+        let peer = cluster.node(1).libp2p_peer_id;
+        assert!(cluster.bus().pending_events(&peer) > 0);
+        assert!(cluster.deliver_events().await >= 2);
+        assert_eq!(cluster.bus().pending_events(&peer), 0);
+
+        // The announcement is cached without touching the DHT
+        let announcement = cluster.node(2).ops().await.state().get_announcement(&hash);
+        assert!(announcement.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_tombstones_withdraw_cached_content() {
+        let cluster = TestCluster::new(2);
+        let hash = cluster
+            .publish(0, b"to be erased", "Erased", 0)
+            .await
+            .unwrap();
+        cluster.deliver_events().await;
+        let response = cluster.query(1, &hash, 0).await.unwrap();
+        assert_eq!(response.content, b"to be erased");
+        assert!(cluster.node(1).ops().await.is_content_cached(&hash));
+
+        cluster
+            .node(0)
+            .ops()
+            .await
+            .delete_content(&hash)
+            .await
+            .unwrap();
+        cluster.deliver_events().await;
+
+        // The peer drops its copy and the announcement
+        let ops = cluster.node(1).ops().await;
+        assert!(!ops.is_content_cached(&hash));
+        assert!(ops.state().get_announcement(&hash).is_none());
+        assert!(ops.get_tombstone(&hash).unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_reports_reach_moderation_queues() {
+        let cluster = TestCluster::new(3);
+        let hash = cluster
+            .publish(0, b"cheap pills", "Pills", 0)
+            .await
+            .unwrap();
+        cluster.deliver_events().await;
+
+        cluster
+            .node(2)
+            .ops()
+            .await
+            .report_content(&hash, nodalync_types::ReportReason::Spam, "Link farm")
+            .await
+            .unwrap();
+        cluster.deliver_events().await;
+
+        // Both the peer that saw the announcement and the owner queue it
+        for node in [0, 1] {
+            let ops = cluster.node(node).ops().await;
+            let reports = ops.get_reports(&hash).unwrap();
+            assert_eq!(reports.len(), 1);
+            assert_eq!(reports[0].reporter, cluster.node(2).peer_id);
+            assert_eq!(ops.moderation_queue().unwrap().len(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_paid_query_requires_channel() {
+        let cluster = TestCluster::new(2);
+        let price = 1_0000_0000;
+        let hash = cluster
+            .publish(0, b"premium", "Premium", price)
+            .await
+            .unwrap();
+
+        let result = cluster.query(1, &hash, price).await;
+        assert!(matches!(
+            result,
+            Err(OpsError::ChannelRequired | OpsError::ChannelRequiredWithPeerInfo { .. })
+        ));
+
+        cluster.open_channel(1, 0, 200_0000_0000).await.unwrap();
+        let response = cluster.query(1, &hash, price).await.unwrap();
+        assert_eq!(response.content, b"premium");
+        assert_eq!(response.receipt.amount, price);
+    }
+
+    #[tokio::test]
+    async fn test_paid_query_answers_challenge() {
+        let cluster = TestCluster::new(2);
+        let price = 1_0000_0000;
+        cluster.node(0).ops().await.config.query_challenge =
+            nodalync_ops::QueryChallengeConfig::default().with_min_price(price);
+        let hash = cluster
+            .publish(0, b"premium", "Premium", price)
+            .await
+            .unwrap();
+
+        cluster.open_channel(1, 0, 200_0000_0000).await.unwrap();
+        let response = cluster.query(1, &hash, price).await.unwrap();
+        assert_eq!(response.content, b"premium");
+        assert_eq!(cluster.node(0).settlement.settled_batches().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_paid_collection_delivers_bundle() {
+        let cluster = TestCluster::new(2);
+        let a = cluster
+            .publish(0, b"first paper", "First", 0)
+            .await
+            .unwrap();
+        let b = cluster
+            .publish(0, b"second paper", "Second", 0)
+            .await
+            .unwrap();
+
+        let price = 1_0000_0000;
+        let collection = {
+            let ops = cluster.node(0).ops().await;
+            let hash = ops
+                .create_collection(
+                    Collection::new("Reading list")
+                        .with_item(CollectionItem::new(a, "First"))
+                        .with_item(CollectionItem::new(b, "Second").with_weight(3))
+                        .with_bundle_price(price),
+                )
+                .unwrap();
+            ops.publish_content(&hash, Visibility::Shared, price)
+                .await
+                .unwrap();
+            hash
+        };
+
+        cluster.open_channel(1, 0, 200_0000_0000).await.unwrap();
+        let response = cluster.query(1, &collection, price).await.unwrap();
+        assert_eq!(response.bundle, vec![a, b]);
+
+        let ops = cluster.node(1).ops().await;
+        assert!(ops.is_content_cached(&a));
+        assert!(ops.is_content_cached(&b));
+        assert_eq!(ops.get_collection(&collection).unwrap().items.len(), 2);
+
+        // One settlement for the whole bundle
+        let batches = cluster.node(0).settlement.settled_batches();
+        assert_eq!(batches.len(), 1);
+        let total: Amount = batches[0].entries.iter().map(|e| e.amount).sum();
+        assert_eq!(total, price);
+    }
+
+    #[tokio::test]
+    async fn test_close_all_channels_concurrently() {
+        let cluster = TestCluster::new(4);
+        for to in 1..4 {
+            cluster.open_channel(0, to, 200_0000_0000).await.unwrap();
+        }
+
+        // Finish the open as the accept response would
+        let me = cluster.node(0).peer_id;
+        let ops = cluster.node(0).ops().await;
+        for to in 1..4 {
+            let peer = cluster.node(to).peer_id;
+            let accepted = cluster.node(to).ops().await.state.channels.get(&me);
+            let accepted = accepted.unwrap().unwrap();
+            let mut channel = ops.state.channels.get(&peer).unwrap().unwrap();
+            channel.state = ChannelState::Open;
+            channel.their_balance = accepted.my_balance;
+            ops.state.channels.update(&peer, &channel).unwrap();
+        }
+        let private_key = ops.private_key().cloned().unwrap();
+
+        let report = ops.close_all_payment_channels(&private_key).await.unwrap();
+        assert_eq!(report.closed.len(), 3, "{:?}", report);
+        assert!(ops.state.channels.list_open().unwrap().is_empty());
+    }
+
+    /// Open a channel and finish the open as the accept response would,
+    /// so the opener can pay through it.
+    async fn open_relay_channel(cluster: &TestCluster, from: usize, to: usize) {
+        cluster.open_channel(from, to, 200_0000_0000).await.unwrap();
+        let (me, peer) = (cluster.node(from).peer_id, cluster.node(to).peer_id);
+        let accepted = cluster.node(to).ops().await.state.channels.get(&me);
+        let accepted = accepted.unwrap().unwrap();
+        let ops = cluster.node(from).ops().await;
+        let mut channel = ops.state.channels.get(&peer).unwrap().unwrap();
+        channel.state = ChannelState::Open;
+        channel.their_balance = accepted.my_balance;
+        ops.state.channels.update(&peer, &channel).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_query_through_relays() {
+        let cluster = TestCluster::new(4);
+        let price = 1_0000_0000;
+        let fee = 1_000_000;
+        let hash = cluster
+            .publish(0, b"sensitive research", "Sensitive", price)
+            .await
+            .unwrap();
+
+        // Node 3 queries through relays 1 and 2, each paid by the hop before
+        cluster
+            .node(1)
+            .network
+            .set_relay_config(RelayConfig::default().with_serve(fee));
+        cluster
+            .node(2)
+            .network
+            .set_relay_config(RelayConfig::default().with_serve(fee));
+        let route = vec![cluster.node(1).peer_id, cluster.node(2).peer_id];
+        cluster
+            .node(3)
+            .network
+            .set_relay_config(RelayConfig::default().with_route(route, fee));
+        for (from, to) in [(3, 1), (1, 2), (2, 0)] {
+            open_relay_channel(&cluster, from, to).await;
+        }
+
+        let response = cluster.query(3, &hash, price).await.unwrap();
+        assert_eq!(response.content, b"sensitive research");
+        assert!(cluster.node(3).ops().await.is_content_cached(&hash));
+
+        // The publisher only saw the exit relay
+        let accesses = cluster
+            .node(0)
+            .ops()
+            .await
+            .state()
+            .access_log
+            .for_content(&hash)
+            .unwrap();
+        let requesters: Vec<_> = accesses.iter().map(|a| a.requester).collect();
+        assert!(requesters.contains(&AccessRequester::Peer(cluster.node(2).peer_id)));
+        assert!(!requesters.contains(&AccessRequester::Peer(cluster.node(3).peer_id)));
+
+        // Each hop paid its relay's fee on top of what it passed on
+        for (from, to, spent) in [(3, 1, price + 2 * fee), (1, 2, price + fee), (2, 0, price)] {
+            let peer = cluster.node(to).peer_id;
+            let channel = cluster.node(from).ops().await.state().channels.get(&peer);
+            assert_eq!(200_0000_0000 - channel.unwrap().unwrap().my_balance, spent);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_relay_refuses_when_not_serving() {
+        let cluster = TestCluster::new(3);
+        let hash = cluster.publish(0, b"public", "Public", 0).await.unwrap();
+        let route = vec![cluster.node(1).peer_id];
+        cluster
+            .node(2)
+            .network
+            .set_relay_config(RelayConfig::default().with_route(route, 0));
+        open_relay_channel(&cluster, 2, 1).await;
+
+        let result = cluster.query(2, &hash, 0).await;
+        assert!(matches!(
+            result,
+            Err(OpsError::Network(NetworkError::QueryError { .. }))
+        ));
+        assert!(!cluster.node(2).ops().await.is_content_cached(&hash));
+    }
+
+    #[tokio::test]
+    async fn test_send_to_unknown_peer() {
+        let cluster = TestCluster::new(1);
+        let network = &cluster.node(0).network;
+        let result = network
+            .send_search(
+                libp2p::PeerId::random(),
+                SearchPayload {
+                    query: "anything".to_string(),
+                    filters: None,
+                    limit: 10,
+                    offset: 0,
+                },
+            )
+            .await;
+        assert!(matches!(result, Err(NetworkError::PeerNotFound(_))));
+    }
+}
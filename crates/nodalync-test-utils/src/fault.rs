@@ -0,0 +1,348 @@
+//! Deterministic fault injection for the mock network and settlement layers.
+//!
+//! A [`FaultInjector`] decides, from a seeded random number generator,
+//! whether each mock operation is dropped, delayed, duplicated, reordered,
+//! or fails transiently. The same seed and the same sequence of operations
+//! always produce the same faults, so retry and idempotency logic can be
+//! tested reproducibly.
+//!
+//! ```ignore
+//! let faults = FaultInjector::new(42)
+//!     .with_drop_rate(0.2)
+//!     .with_latency(LatencyDistribution::Uniform {
+//!         min: Duration::from_millis(5),
+//!         max: Duration::from_millis(50),
+//!     });
+//! let network = MockNetwork::new().with_fault_injector(faults.clone());
+//! ```
+//!
+//! The injector is cheap to clone and all clones share the same generator
+//! and statistics, so one injector can drive several mocks.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Latency added to each mock operation.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LatencyDistribution {
+    /// No added latency.
+    #[default]
+    None,
+    /// The same delay for every operation.
+    Fixed(Duration),
+    /// A delay drawn uniformly from `[min, max]`.
+    Uniform {
+        /// Shortest delay.
+        min: Duration,
+        /// Longest delay.
+        max: Duration,
+    },
+    /// A delay drawn from an exponential distribution with the given mean,
+    /// giving mostly short delays with an occasional long tail.
+    Exponential {
+        /// Mean delay.
+        mean: Duration,
+    },
+}
+
+/// A fault to apply to a settlement operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettlementFault {
+    /// The operation fails without taking effect.
+    Fail,
+    /// The operation takes effect but the caller receives an error, as if
+    /// the confirmation was lost.
+    LostAck,
+}
+
+/// Counts of faults injected so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FaultStats {
+    /// Operations that were dropped.
+    pub dropped: u64,
+    /// Operations that were delayed.
+    pub delayed: u64,
+    /// Messages or events delivered twice.
+    pub duplicated: u64,
+    /// Messages or events delivered out of order.
+    pub reordered: u64,
+    /// Settlement operations that failed without taking effect.
+    pub settlement_failures: u64,
+    /// Settlement operations that took effect but reported an error.
+    pub lost_acks: u64,
+}
+
+struct FaultInjectorInner {
+    rng: StdRng,
+    enabled: bool,
+    drop_rate: f64,
+    duplicate_rate: f64,
+    reorder_rate: f64,
+    latency: LatencyDistribution,
+    settlement_failure_rate: f64,
+    lost_ack_rate: f64,
+    /// Number of upcoming settlement operations that fail unconditionally.
+    fail_next: u32,
+    stats: FaultStats,
+}
+
+impl FaultInjectorInner {
+    /// Draw a value and return true with probability `rate`.
+    ///
+    /// Always draws, even for a zero rate, so that changing one rate does
+    /// not shift the random sequence seen by the others.
+    fn roll(&mut self, rate: f64) -> bool {
+        let value: f64 = self.rng.gen();
+        self.enabled && value < rate
+    }
+}
+
+/// Seeded, deterministic fault injector shared by the mocks.
+///
+/// All rates are probabilities in `[0.0, 1.0]` and default to zero, so a new
+/// injector injects nothing until configured.
+#[derive(Clone)]
+pub struct FaultInjector {
+    inner: Arc<Mutex<FaultInjectorInner>>,
+}
+
+impl FaultInjector {
+    /// Create an injector with no faults configured.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(FaultInjectorInner {
+                rng: StdRng::seed_from_u64(seed),
+                enabled: true,
+                drop_rate: 0.0,
+                duplicate_rate: 0.0,
+                reorder_rate: 0.0,
+                latency: LatencyDistribution::None,
+                settlement_failure_rate: 0.0,
+                lost_ack_rate: 0.0,
+                fail_next: 0,
+                stats: FaultStats::default(),
+            })),
+        }
+    }
+
+    // =========================================================================
+    // Builder Methods
+    // =========================================================================
+
+    /// Set the probability that a network operation is dropped.
+    pub fn with_drop_rate(self, rate: f64) -> Self {
+        self.inner.lock().unwrap().drop_rate = rate;
+        self
+    }
+
+    /// Set the probability that a message or event is delivered twice.
+    pub fn with_duplicate_rate(self, rate: f64) -> Self {
+        self.inner.lock().unwrap().duplicate_rate = rate;
+        self
+    }
+
+    /// Set the probability that a message or event is delivered before the
+    /// one preceding it.
+    pub fn with_reorder_rate(self, rate: f64) -> Self {
+        self.inner.lock().unwrap().reorder_rate = rate;
+        self
+    }
+
+    /// Set the latency added to each operation.
+    pub fn with_latency(self, latency: LatencyDistribution) -> Self {
+        self.inner.lock().unwrap().latency = latency;
+        self
+    }
+
+    /// Set the probability that a settlement operation fails transiently.
+    pub fn with_settlement_failure_rate(self, rate: f64) -> Self {
+        self.inner.lock().unwrap().settlement_failure_rate = rate;
+        self
+    }
+
+    /// Set the probability that a settlement operation succeeds but reports
+    /// an error to the caller.
+    pub fn with_lost_ack_rate(self, rate: f64) -> Self {
+        self.inner.lock().unwrap().lost_ack_rate = rate;
+        self
+    }
+
+    // =========================================================================
+    // Runtime Control
+    // =========================================================================
+
+    /// Make the next `n` settlement operations fail transiently.
+    pub fn fail_next_settlements(&self, n: u32) {
+        self.inner.lock().unwrap().fail_next = n;
+    }
+
+    /// Enable or disable all faults without changing the configuration.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.inner.lock().unwrap().enabled = enabled;
+    }
+
+    /// Get the faults injected so far.
+    pub fn stats(&self) -> FaultStats {
+        self.inner.lock().unwrap().stats
+    }
+
+    // =========================================================================
+    // Decisions (used by the mocks)
+    // =========================================================================
+
+    /// Decide whether to drop the current operation.
+    pub fn should_drop(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let rate = inner.drop_rate;
+        let drop = inner.roll(rate);
+        if drop {
+            inner.stats.dropped += 1;
+        }
+        drop
+    }
+
+    /// Decide whether to deliver the current message twice.
+    pub fn should_duplicate(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let rate = inner.duplicate_rate;
+        let duplicate = inner.roll(rate);
+        if duplicate {
+            inner.stats.duplicated += 1;
+        }
+        duplicate
+    }
+
+    /// Decide whether to deliver the current message before the previous one.
+    pub fn should_reorder(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let rate = inner.reorder_rate;
+        let reorder = inner.roll(rate);
+        if reorder {
+            inner.stats.reordered += 1;
+        }
+        reorder
+    }
+
+    /// Draw the latency for the current operation.
+    pub fn next_latency(&self) -> Duration {
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.enabled {
+            return Duration::ZERO;
+        }
+        let delay = match inner.latency {
+            LatencyDistribution::None => Duration::ZERO,
+            LatencyDistribution::Fixed(delay) => delay,
+            LatencyDistribution::Uniform { min, max } => {
+                if max <= min {
+                    min
+                } else {
+                    inner.rng.gen_range(min..=max)
+                }
+            }
+            LatencyDistribution::Exponential { mean } => {
+                let u: f64 = inner.rng.gen();
+                mean.mul_f64(-(1.0 - u).ln())
+            }
+        };
+        if !delay.is_zero() {
+            inner.stats.delayed += 1;
+        }
+        delay
+    }
+
+    /// Sleep for the drawn latency, if any.
+    pub async fn delay(&self) {
+        let delay = self.next_latency();
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Decide the fault, if any, for the current settlement operation.
+    pub fn settlement_fault(&self) -> Option<SettlementFault> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.enabled && inner.fail_next > 0 {
+            inner.fail_next -= 1;
+            inner.stats.settlement_failures += 1;
+            return Some(SettlementFault::Fail);
+        }
+        let failure_rate = inner.settlement_failure_rate;
+        if inner.roll(failure_rate) {
+            inner.stats.settlement_failures += 1;
+            return Some(SettlementFault::Fail);
+        }
+        let lost_ack_rate = inner.lost_ack_rate;
+        if inner.roll(lost_ack_rate) {
+            inner.stats.lost_acks += 1;
+            return Some(SettlementFault::LostAck);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drop_pattern(seed: u64) -> Vec<bool> {
+        let faults = FaultInjector::new(seed).with_drop_rate(0.5);
+        (0..64).map(|_| faults.should_drop()).collect()
+    }
+
+    #[test]
+    fn test_same_seed_same_faults() {
+        assert_eq!(drop_pattern(7), drop_pattern(7));
+        assert_ne!(drop_pattern(7), drop_pattern(8));
+    }
+
+    #[test]
+    fn test_default_injects_nothing() {
+        let faults = FaultInjector::new(1);
+        for _ in 0..100 {
+            assert!(!faults.should_drop());
+            assert!(!faults.should_duplicate());
+            assert!(!faults.should_reorder());
+            assert_eq!(faults.next_latency(), Duration::ZERO);
+            assert_eq!(faults.settlement_fault(), None);
+        }
+        assert_eq!(faults.stats(), FaultStats::default());
+    }
+
+    #[test]
+    fn test_rates_are_respected() {
+        let faults = FaultInjector::new(3).with_drop_rate(1.0);
+        assert!((0..10).all(|_| faults.should_drop()));
+        assert_eq!(faults.stats().dropped, 10);
+
+        faults.set_enabled(false);
+        assert!(!faults.should_drop());
+    }
+
+    #[test]
+    fn test_latency_distributions() {
+        let min = Duration::from_millis(10);
+        let max = Duration::from_millis(20);
+        let faults = FaultInjector::new(5).with_latency(LatencyDistribution::Uniform { min, max });
+        for _ in 0..50 {
+            let delay = faults.next_latency();
+            assert!(delay >= min && delay <= max);
+        }
+
+        let fixed = Duration::from_millis(3);
+        let faults = FaultInjector::new(5).with_latency(LatencyDistribution::Fixed(fixed));
+        assert_eq!(faults.next_latency(), fixed);
+        assert_eq!(faults.stats().delayed, 1);
+    }
+
+    #[test]
+    fn test_fail_next_settlements() {
+        let faults = FaultInjector::new(9);
+        faults.fail_next_settlements(2);
+        assert_eq!(faults.settlement_fault(), Some(SettlementFault::Fail));
+        assert_eq!(faults.settlement_fault(), Some(SettlementFault::Fail));
+        assert_eq!(faults.settlement_fault(), None);
+        assert_eq!(faults.stats().settlement_failures, 2);
+    }
+}
@@ -159,6 +159,9 @@ pub fn test_announce_payload(hash: Hash, title: &str, price: Amount) -> Announce
         price,
         addresses: vec!["/ip4/127.0.0.1/tcp/9000".to_string()],
         publisher_peer_id: None,
+        publisher_key: None,
+        signature: None,
+        delegation: None,
     }
 }
 
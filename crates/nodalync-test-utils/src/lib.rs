@@ -1,7 +1,13 @@
+pub mod cluster;
+pub mod fault;
 pub mod helpers;
 pub mod mock_network;
+pub mod mock_operations;
 pub mod mock_settlement;
 
+pub use cluster::{BusNetwork, ClusterNode, MemoryBus, TestCluster};
+pub use fault::{FaultInjector, FaultStats, LatencyDistribution, SettlementFault};
 pub use helpers::*;
 pub use mock_network::MockNetwork;
+pub use mock_operations::{MockOperations, OperationCall, OperationKind};
 pub use mock_settlement::MockSettlement;
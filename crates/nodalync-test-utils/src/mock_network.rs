@@ -1,23 +1,38 @@
 //! Mock implementation of the `Network` trait for testing.
 //!
 //! Provides a configurable mock network that records sent messages
-//! and returns pre-configured responses.
+//! and returns pre-configured responses. An optional [`FaultInjector`]
+//! drops, delays, duplicates, and reorders traffic.
 
 use async_trait::async_trait;
 use libp2p::Multiaddr;
 use nodalync_crypto::{Hash, PeerId as NodalyncPeerId};
-use nodalync_net::{Network, NetworkError, NetworkEvent, NetworkResult};
+use nodalync_net::{Network, NetworkError, NetworkEvent, NetworkResult, RelayConfig};
+use nodalync_types::{ErrorCode, Group, CHUNK_SIZE};
 use nodalync_wire::{
-    AnnouncePayload, ChannelClosePayload, ChannelOpenPayload, Message, MessageType,
-    PreviewRequestPayload, PreviewResponsePayload, QueryRequestPayload, QueryResponsePayload,
-    SearchPayload, SearchResponsePayload, SettleConfirmPayload,
+    AnnouncePayload, AvailabilityCheckPayload, AvailabilityResultPayload, ChannelClosePayload,
+    ChannelOpenPayload, ChannelSyncPayload, ChannelSyncResponsePayload, ChunkRequestPayload,
+    ChunkResponsePayload, EncryptedSyncBundle, FraudProofPayload, GroupRequestPayload,
+    GroupResponsePayload, InvoiceAckPayload, InvoicePayPayload, InvoicePayload,
+    InvoiceRequestPayload, Message, MessageType, PeerInfoPayload, PingPayload, PongPayload,
+    PreviewRequestPayload, PreviewResponsePayload, QueryErrorPayload, QueryRequestPayload,
+    QueryResponsePayload, RelayQueryPayload, RelayResponsePayload, ReportPayload,
+    RevocationPayload, SearchPayload, SearchResponsePayload, SettleConfirmPayload,
+    SnapshotRequestPayload, SnapshotResponsePayload, StateSnapshot, Storefront, SyncPullPayload,
+    SyncPullResponsePayload, SyncPushAckPayload, SyncPushPayload, TombstonePayload,
+    UsageReportAckPayload, UsageReportPayload, VersionRequestPayload, VersionResponsePayload,
 };
 use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::fault::FaultInjector;
 
 struct MockNetworkInner {
     /// DHT storage: hash -> AnnouncePayload.
     dht: HashMap<Hash, AnnouncePayload>,
+    /// DHT storefronts: publisher -> Storefront.
+    storefronts: HashMap<NodalyncPeerId, Storefront>,
     /// Sent messages for assertion (spy pattern).
     sent_messages: Vec<(libp2p::PeerId, Message)>,
     /// Broadcast messages for assertion.
@@ -26,12 +41,68 @@ struct MockNetworkInner {
     preview_responses: HashMap<Hash, PreviewResponsePayload>,
     /// Configurable query responses keyed by content hash.
     query_responses: HashMap<Hash, QueryResponsePayload>,
+    /// Per-peer query errors, each answering one query before the
+    /// configured responses.
+    query_errors: HashMap<libp2p::PeerId, VecDeque<QueryErrorPayload>>,
+    /// Queries sent, in order, with the peer they were sent to.
+    query_requests: Vec<(libp2p::PeerId, QueryRequestPayload)>,
     /// Configurable search responses keyed by query string.
     search_responses: HashMap<String, SearchResponsePayload>,
+    /// Per-peer search responses, taking precedence over `search_responses`.
+    peer_search_responses: HashMap<(libp2p::PeerId, String), SearchResponsePayload>,
+    /// Per-peer response latency for search requests.
+    peer_latency: HashMap<libp2p::PeerId, Duration>,
+    /// Configurable version responses keyed by version root.
+    version_responses: HashMap<Hash, VersionResponsePayload>,
     /// Configurable channel open responses keyed by channel ID hash.
     channel_open_responses: HashMap<Hash, Message>,
     /// Configurable channel close responses keyed by channel ID hash.
     channel_close_responses: HashMap<Hash, Message>,
+    /// Configurable channel sync responses keyed by channel ID hash.
+    channel_sync_responses: HashMap<Hash, ChannelSyncResponsePayload>,
+    /// Configurable invoice request responses keyed by invoice hash.
+    invoice_responses: HashMap<Hash, Message>,
+    /// Configurable acks for sent invoices and invoice payments, keyed by
+    /// invoice hash.
+    invoice_acks: HashMap<Hash, InvoiceAckPayload>,
+    /// Usage reports sent, in order (always acknowledged as accepted).
+    usage_reports: Vec<(libp2p::PeerId, UsageReportPayload)>,
+    /// Relayed queries sent, in order (never answered).
+    relay_queries: Vec<(libp2p::PeerId, RelayQueryPayload)>,
+    /// Content each peer serves chunks of, keyed by peer and content hash.
+    chunk_sources: HashMap<(libp2p::PeerId, Hash), Vec<u8>>,
+    /// Chunk requests sent, in order.
+    chunk_requests: Vec<(libp2p::PeerId, ChunkRequestPayload)>,
+    /// Relayed query configuration.
+    relay: RelayConfig,
+    /// How far each peer's clock is ahead of ours, in milliseconds. Peers
+    /// not listed don't answer pings.
+    peer_clock_offsets: HashMap<libp2p::PeerId, i64>,
+    /// Peer info each peer answers with. Other peers don't answer.
+    peer_infos: HashMap<libp2p::PeerId, PeerInfoPayload>,
+    /// Peer info sent, in order.
+    sent_peer_infos: Vec<(libp2p::PeerId, PeerInfoPayload)>,
+    /// Clock offset last set for outgoing messages.
+    clock_offset: i64,
+    /// Broadcast backpressure last set by the ops layer.
+    broadcast_backpressure: bool,
+    /// Membership lists returned for group requests, keyed by group ID.
+    groups: HashMap<Hash, Group>,
+    /// Group IDs requested, in order.
+    group_requests: Vec<Hash>,
+    /// Snapshots returned for snapshot requests, keyed by peer. Other
+    /// peers don't serve snapshots.
+    snapshots: HashMap<libp2p::PeerId, StateSnapshot>,
+    /// Sync bundles pushed, keyed by owner. Every peer acts as one shared
+    /// mailbox, keeping the newest bundle per owner.
+    sync_bundles: HashMap<NodalyncPeerId, EncryptedSyncBundle>,
+    /// Addresses dialed, in order.
+    dialed: Vec<Multiaddr>,
+    /// Availability results returned for checks, keyed by content hash.
+    /// Checks for other hashes time out.
+    availability_results: HashMap<Hash, AvailabilityResultPayload>,
+    /// Availability checks sent, with the checker asked, in order.
+    availability_checks: Vec<(libp2p::PeerId, AvailabilityCheckPayload)>,
     /// Peer ID mappings: Nodalync -> libp2p.
     nodalync_to_libp2p: HashMap<NodalyncPeerId, libp2p::PeerId>,
     /// Peer ID mappings: libp2p -> Nodalync.
@@ -48,19 +119,47 @@ struct MockNetworkInner {
     raw_responses: Vec<Vec<u8>>,
     /// Recorded signed responses sent via send_signed_response.
     signed_responses: Vec<(MessageType, Vec<u8>)>,
+    /// Optional fault injector applied to DHT operations, messages, and events.
+    faults: Option<FaultInjector>,
 }
 
 impl MockNetworkInner {
     fn new(local_peer_id: libp2p::PeerId) -> Self {
         Self {
             dht: HashMap::new(),
+            storefronts: HashMap::new(),
             sent_messages: Vec::new(),
             broadcast_messages: Vec::new(),
             preview_responses: HashMap::new(),
             query_responses: HashMap::new(),
+            query_errors: HashMap::new(),
+            query_requests: Vec::new(),
             search_responses: HashMap::new(),
+            peer_search_responses: HashMap::new(),
+            peer_latency: HashMap::new(),
+            version_responses: HashMap::new(),
             channel_open_responses: HashMap::new(),
             channel_close_responses: HashMap::new(),
+            channel_sync_responses: HashMap::new(),
+            invoice_responses: HashMap::new(),
+            invoice_acks: HashMap::new(),
+            usage_reports: Vec::new(),
+            relay_queries: Vec::new(),
+            chunk_sources: HashMap::new(),
+            chunk_requests: Vec::new(),
+            relay: RelayConfig::default(),
+            peer_clock_offsets: HashMap::new(),
+            peer_infos: HashMap::new(),
+            sent_peer_infos: Vec::new(),
+            clock_offset: 0,
+            broadcast_backpressure: false,
+            groups: HashMap::new(),
+            group_requests: Vec::new(),
+            snapshots: HashMap::new(),
+            availability_results: HashMap::new(),
+            availability_checks: Vec::new(),
+            sync_bundles: HashMap::new(),
+            dialed: Vec::new(),
             nodalync_to_libp2p: HashMap::new(),
             libp2p_to_nodalync: HashMap::new(),
             connected_peers: Vec::new(),
@@ -69,10 +168,59 @@ impl MockNetworkInner {
             listen_addresses: Vec::new(),
             raw_responses: Vec::new(),
             signed_responses: Vec::new(),
+            faults: None,
         }
     }
 }
 
+/// A list of delivered items (sent messages or queued events).
+trait DeliveryQueue<T> {
+    fn push_item(&mut self, item: T);
+    fn swap_last_two(&mut self);
+}
+
+impl<T> DeliveryQueue<T> for Vec<T> {
+    fn push_item(&mut self, item: T) {
+        self.push(item);
+    }
+
+    fn swap_last_two(&mut self) {
+        let len = self.len();
+        if len >= 2 {
+            self.swap(len - 1, len - 2);
+        }
+    }
+}
+
+impl<T> DeliveryQueue<T> for VecDeque<T> {
+    fn push_item(&mut self, item: T) {
+        self.push_back(item);
+    }
+
+    fn swap_last_two(&mut self) {
+        let len = self.len();
+        if len >= 2 {
+            self.swap(len - 1, len - 2);
+        }
+    }
+}
+
+/// Append an item, duplicating it or swapping it with the previous item as
+/// decided by the fault injector.
+fn deliver<T: Clone>(queue: &mut impl DeliveryQueue<T>, item: T, faults: Option<&FaultInjector>) {
+    let Some(faults) = faults else {
+        queue.push_item(item);
+        return;
+    };
+    if faults.should_duplicate() {
+        queue.push_item(item.clone());
+    }
+    queue.push_item(item);
+    if faults.should_reorder() {
+        queue.swap_last_two();
+    }
+}
+
 /// A mock implementation of the `Network` trait for testing.
 ///
 /// Records all sent messages and returns pre-configured responses.
@@ -128,6 +276,19 @@ impl MockNetwork {
         self
     }
 
+    /// Queue a query error from `peer`, answering its next query that
+    /// isn't answered by an earlier queued error.
+    pub fn with_query_error(self, peer: libp2p::PeerId, error: QueryErrorPayload) -> Self {
+        self.inner
+            .lock()
+            .unwrap()
+            .query_errors
+            .entry(peer)
+            .or_default()
+            .push_back(error);
+        self
+    }
+
     /// Add a pre-configured search response for a given query string.
     pub fn with_search_response(self, query: String, response: SearchResponsePayload) -> Self {
         self.inner
@@ -138,6 +299,45 @@ impl MockNetwork {
         self
     }
 
+    /// Add a search response that only the given peer returns for a query.
+    pub fn with_peer_search_response(
+        self,
+        peer: libp2p::PeerId,
+        query: String,
+        response: SearchResponsePayload,
+    ) -> Self {
+        self.inner
+            .lock()
+            .unwrap()
+            .peer_search_responses
+            .insert((peer, query), response);
+        self
+    }
+
+    /// Delay a peer's search responses.
+    pub fn with_peer_latency(self, peer: libp2p::PeerId, latency: Duration) -> Self {
+        self.inner
+            .lock()
+            .unwrap()
+            .peer_latency
+            .insert(peer, latency);
+        self
+    }
+
+    /// Add a pre-configured version response for a given version root.
+    pub fn with_version_response(
+        self,
+        version_root: Hash,
+        response: VersionResponsePayload,
+    ) -> Self {
+        self.inner
+            .lock()
+            .unwrap()
+            .version_responses
+            .insert(version_root, response);
+        self
+    }
+
     /// Add a pre-configured channel open response for a given channel ID.
     pub fn with_channel_open_response(self, channel_id: Hash, response: Message) -> Self {
         self.inner
@@ -158,6 +358,97 @@ impl MockNetwork {
         self
     }
 
+    /// Add a pre-configured channel sync response for a given channel ID.
+    pub fn with_channel_sync_response(self, response: ChannelSyncResponsePayload) -> Self {
+        self.inner
+            .lock()
+            .unwrap()
+            .channel_sync_responses
+            .insert(response.channel_id, response);
+        self
+    }
+
+    /// Add a pre-configured response to an invoice request.
+    pub fn with_invoice_response(self, invoice_hash: Hash, response: Message) -> Self {
+        self.inner
+            .lock()
+            .unwrap()
+            .invoice_responses
+            .insert(invoice_hash, response);
+        self
+    }
+
+    /// Add a pre-configured ack for a sent invoice or invoice payment.
+    pub fn with_invoice_ack(self, ack: InvoiceAckPayload) -> Self {
+        self.inner
+            .lock()
+            .unwrap()
+            .invoice_acks
+            .insert(ack.invoice_hash, ack);
+        self
+    }
+
+    /// Add (or replace) the membership list returned for its group ID.
+    pub fn with_group(self, group: Group) -> Self {
+        self.set_group(group);
+        self
+    }
+
+    /// Replace the membership list returned for its group ID.
+    pub fn set_group(&self, group: Group) {
+        self.inner.lock().unwrap().groups.insert(group.id, group);
+    }
+
+    /// Add (or replace) the snapshot a peer returns for snapshot requests.
+    pub fn with_snapshot(self, peer: libp2p::PeerId, snapshot: StateSnapshot) -> Self {
+        self.inner.lock().unwrap().snapshots.insert(peer, snapshot);
+        self
+    }
+
+    /// Add (or replace) the result returned for availability checks of a hash.
+    pub fn with_availability_result(self, result: AvailabilityResultPayload) -> Self {
+        self.inner
+            .lock()
+            .unwrap()
+            .availability_results
+            .insert(result.hash, result);
+        self
+    }
+
+    /// Set the relayed query configuration.
+    pub fn with_relay_config(self, config: RelayConfig) -> Self {
+        self.inner.lock().unwrap().relay = config;
+        self
+    }
+
+    /// Serve chunk requests to `peer` for `hash` from `content`, split into
+    /// `CHUNK_SIZE` chunks. Pass altered bytes to simulate a corrupt
+    /// provider.
+    pub fn with_chunk_source(self, peer: libp2p::PeerId, hash: Hash, content: Vec<u8>) -> Self {
+        self.inner
+            .lock()
+            .unwrap()
+            .chunk_sources
+            .insert((peer, hash), content);
+        self
+    }
+
+    /// Answer a peer's pings with its clock `offset_ms` ahead of ours.
+    pub fn with_peer_clock_offset(self, peer: libp2p::PeerId, offset_ms: i64) -> Self {
+        self.inner
+            .lock()
+            .unwrap()
+            .peer_clock_offsets
+            .insert(peer, offset_ms);
+        self
+    }
+
+    /// Answer peer info sent to `peer` with `info`.
+    pub fn with_peer_info(self, peer: libp2p::PeerId, info: PeerInfoPayload) -> Self {
+        self.inner.lock().unwrap().peer_infos.insert(peer, info);
+        self
+    }
+
     /// Add a connected peer.
     pub fn with_connected_peer(self, peer: libp2p::PeerId) -> Self {
         self.inner.lock().unwrap().connected_peers.push(peer);
@@ -170,9 +461,27 @@ impl MockNetwork {
         self
     }
 
+    /// Attach a fault injector.
+    ///
+    /// DHT operations and requests may then be delayed or fail with
+    /// `NetworkError::Timeout`, broadcasts may be silently lost, and sent
+    /// messages and enqueued events may be duplicated or reordered.
+    pub fn with_fault_injector(self, faults: FaultInjector) -> Self {
+        self.inner.lock().unwrap().faults = Some(faults);
+        self
+    }
+
     /// Enqueue a network event to be returned by `next_event`.
+    ///
+    /// With a fault injector attached, the event may be dropped, duplicated,
+    /// or delivered before the previously enqueued event.
     pub fn enqueue_event(&self, event: NetworkEvent) {
-        self.inner.lock().unwrap().events.push_back(event);
+        let mut inner = self.inner.lock().unwrap();
+        let faults = inner.faults.clone();
+        if faults.as_ref().is_some_and(|f| f.should_drop()) {
+            return;
+        }
+        deliver(&mut inner.events, event, faults.as_ref());
     }
 
     /// Add a DHT entry directly.
@@ -181,6 +490,16 @@ impl MockNetwork {
         self
     }
 
+    /// Add a storefront to the DHT directly.
+    pub fn with_storefront(self, storefront: Storefront) -> Self {
+        self.inner
+            .lock()
+            .unwrap()
+            .storefronts
+            .insert(storefront.publisher, storefront);
+        self
+    }
+
     /// Add a peer ID mapping.
     pub fn with_peer_mapping(
         self,
@@ -229,11 +548,106 @@ impl MockNetwork {
         self.inner.lock().unwrap().signed_responses.clone()
     }
 
+    /// Get all usage reports sent, with the peer they were sent to.
+    pub fn usage_reports(&self) -> Vec<(libp2p::PeerId, UsageReportPayload)> {
+        self.inner.lock().unwrap().usage_reports.clone()
+    }
+
+    /// Get all relayed queries sent, with the relay they were sent to.
+    pub fn relay_queries(&self) -> Vec<(libp2p::PeerId, RelayQueryPayload)> {
+        self.inner.lock().unwrap().relay_queries.clone()
+    }
+
+    /// Get all queries sent, with the peer they were sent to.
+    pub fn query_requests(&self) -> Vec<(libp2p::PeerId, QueryRequestPayload)> {
+        self.inner.lock().unwrap().query_requests.clone()
+    }
+
+    /// Get all chunk requests sent, with the peer they were sent to.
+    pub fn chunk_requests(&self) -> Vec<(libp2p::PeerId, ChunkRequestPayload)> {
+        self.inner.lock().unwrap().chunk_requests.clone()
+    }
+
+    /// Get all peer info sent, with the peer it was sent to.
+    pub fn sent_peer_infos(&self) -> Vec<(libp2p::PeerId, PeerInfoPayload)> {
+        self.inner.lock().unwrap().sent_peer_infos.clone()
+    }
+
+    /// Get the clock offset last set for outgoing messages.
+    pub fn clock_offset(&self) -> i64 {
+        self.inner.lock().unwrap().clock_offset
+    }
+
+    /// Whether broadcast backpressure is currently engaged.
+    pub fn broadcast_backpressure(&self) -> bool {
+        self.inner.lock().unwrap().broadcast_backpressure
+    }
+
+    /// Get the group IDs requested, in order.
+    pub fn group_requests(&self) -> Vec<Hash> {
+        self.inner.lock().unwrap().group_requests.clone()
+    }
+
+    /// Get the availability checks sent, with the checker asked, in order.
+    pub fn availability_checks(&self) -> Vec<(libp2p::PeerId, AvailabilityCheckPayload)> {
+        self.inner.lock().unwrap().availability_checks.clone()
+    }
+
+    /// Get the sync bundle held for an owner.
+    pub fn sync_bundle(&self, owner: &NodalyncPeerId) -> Option<EncryptedSyncBundle> {
+        self.inner.lock().unwrap().sync_bundles.get(owner).cloned()
+    }
+
+    /// Get the addresses dialed, in order.
+    pub fn dialed(&self) -> Vec<Multiaddr> {
+        self.inner.lock().unwrap().dialed.clone()
+    }
+
     /// Get the current DHT entries.
     pub fn dht_entries(&self) -> HashMap<Hash, AnnouncePayload> {
         self.inner.lock().unwrap().dht.clone()
     }
 
+    /// Get the storefronts in the DHT.
+    pub fn storefronts(&self) -> HashMap<NodalyncPeerId, Storefront> {
+        self.inner.lock().unwrap().storefronts.clone()
+    }
+
+    /// Get the attached fault injector, if any.
+    pub fn fault_injector(&self) -> Option<FaultInjector> {
+        self.inner.lock().unwrap().faults.clone()
+    }
+
+    /// Apply injected latency and drops to an operation.
+    async fn inject(&self, operation: &str) -> NetworkResult<()> {
+        let Some(faults) = self.fault_injector() else {
+            return Ok(());
+        };
+        faults.delay().await;
+        if faults.should_drop() {
+            return Err(NetworkError::Timeout(format!(
+                "mock: injected drop of {}",
+                operation
+            )));
+        }
+        Ok(())
+    }
+
+    /// Look up the configured ack for an invoice.
+    fn invoice_ack(&self, invoice_hash: &Hash) -> NetworkResult<InvoiceAckPayload> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .invoice_acks
+            .get(invoice_hash)
+            .cloned()
+            .ok_or_else(|| {
+                NetworkError::Timeout(format!(
+                    "no mock invoice ack configured for invoice {}",
+                    invoice_hash
+                ))
+            })
+    }
+
     /// Clear all recorded messages.
     pub fn clear_messages(&self) {
         let mut inner = self.inner.lock().unwrap();
@@ -251,36 +665,68 @@ impl Network for MockNetwork {
     // =========================================================================
 
     async fn dht_announce(&self, hash: Hash, payload: AnnouncePayload) -> NetworkResult<()> {
+        self.inject("dht_announce").await?;
         self.inner.lock().unwrap().dht.insert(hash, payload);
         Ok(())
     }
 
     async fn dht_get(&self, hash: &Hash) -> NetworkResult<Option<AnnouncePayload>> {
+        self.inject("dht_get").await?;
         Ok(self.inner.lock().unwrap().dht.get(hash).cloned())
     }
 
     async fn dht_remove(&self, hash: &Hash) -> NetworkResult<()> {
+        self.inject("dht_remove").await?;
         self.inner.lock().unwrap().dht.remove(hash);
         Ok(())
     }
 
+    async fn dht_put_storefront(&self, storefront: Storefront) -> NetworkResult<()> {
+        self.inject("dht_put_storefront").await?;
+        self.inner
+            .lock()
+            .unwrap()
+            .storefronts
+            .insert(storefront.publisher, storefront);
+        Ok(())
+    }
+
+    async fn dht_get_storefront(
+        &self,
+        publisher: &NodalyncPeerId,
+    ) -> NetworkResult<Option<Storefront>> {
+        self.inject("dht_get_storefront").await?;
+        Ok(self
+            .inner
+            .lock()
+            .unwrap()
+            .storefronts
+            .get(publisher)
+            .cloned())
+    }
+
     // =========================================================================
     // Messaging
     // =========================================================================
 
     async fn send(&self, peer: libp2p::PeerId, message: Message) -> NetworkResult<Message> {
+        self.inject("send").await?;
         let response = message.clone();
-        self.inner
-            .lock()
-            .unwrap()
-            .sent_messages
-            .push((peer, message));
+        let mut inner = self.inner.lock().unwrap();
+        let faults = inner.faults.clone();
+        deliver(&mut inner.sent_messages, (peer, message), faults.as_ref());
         // Return the same message as a default response
         Ok(response)
     }
 
     async fn broadcast(&self, message: Message) -> NetworkResult<()> {
-        self.inner.lock().unwrap().broadcast_messages.push(message);
+        // Gossip is fire-and-forget, so a dropped broadcast is lost silently
+        if self.inject("broadcast").await.is_err() {
+            return Ok(());
+        }
+        let mut inner = self.inner.lock().unwrap();
+        let faults = inner.faults.clone();
+        deliver(&mut inner.broadcast_messages, message, faults.as_ref());
         Ok(())
     }
 
@@ -293,6 +739,7 @@ impl Network for MockNetwork {
         _peer: libp2p::PeerId,
         request: PreviewRequestPayload,
     ) -> NetworkResult<PreviewResponsePayload> {
+        self.inject("send_preview_request").await?;
         let inner = self.inner.lock().unwrap();
         inner
             .preview_responses
@@ -308,10 +755,19 @@ impl Network for MockNetwork {
 
     async fn send_query(
         &self,
-        _peer: libp2p::PeerId,
+        peer: libp2p::PeerId,
         request: QueryRequestPayload,
     ) -> NetworkResult<QueryResponsePayload> {
-        let inner = self.inner.lock().unwrap();
+        self.inject("send_query").await?;
+        let mut inner = self.inner.lock().unwrap();
+        inner.query_requests.push((peer, request.clone()));
+        if let Some(error) = inner
+            .query_errors
+            .get_mut(&peer)
+            .and_then(VecDeque::pop_front)
+        {
+            return Err(error.into());
+        }
         inner
             .query_responses
             .get(&request.hash)
@@ -326,13 +782,19 @@ impl Network for MockNetwork {
 
     async fn send_search(
         &self,
-        _peer: libp2p::PeerId,
+        peer: libp2p::PeerId,
         request: SearchPayload,
     ) -> NetworkResult<SearchResponsePayload> {
+        self.inject("send_search").await?;
+        let latency = self.inner.lock().unwrap().peer_latency.get(&peer).copied();
+        if let Some(latency) = latency {
+            tokio::time::sleep(latency).await;
+        }
         let inner = self.inner.lock().unwrap();
         inner
-            .search_responses
-            .get(&request.query)
+            .peer_search_responses
+            .get(&(peer, request.query.clone()))
+            .or_else(|| inner.search_responses.get(&request.query))
             .cloned()
             .ok_or_else(|| {
                 NetworkError::Timeout(format!(
@@ -342,11 +804,31 @@ impl Network for MockNetwork {
             })
     }
 
+    async fn send_version_request(
+        &self,
+        _peer: libp2p::PeerId,
+        request: VersionRequestPayload,
+    ) -> NetworkResult<VersionResponsePayload> {
+        self.inject("send_version_request").await?;
+        let inner = self.inner.lock().unwrap();
+        inner
+            .version_responses
+            .get(&request.version_root)
+            .cloned()
+            .ok_or_else(|| {
+                NetworkError::Timeout(format!(
+                    "no mock version response configured for root {}",
+                    request.version_root
+                ))
+            })
+    }
+
     async fn send_channel_open(
         &self,
         _peer: libp2p::PeerId,
         payload: ChannelOpenPayload,
     ) -> NetworkResult<Message> {
+        self.inject("send_channel_open").await?;
         let inner = self.inner.lock().unwrap();
         inner
             .channel_open_responses
@@ -365,6 +847,7 @@ impl Network for MockNetwork {
         _peer: libp2p::PeerId,
         payload: ChannelClosePayload,
     ) -> NetworkResult<Message> {
+        self.inject("send_channel_close").await?;
         let inner = self.inner.lock().unwrap();
         inner
             .channel_close_responses
@@ -378,6 +861,246 @@ impl Network for MockNetwork {
             })
     }
 
+    async fn send_channel_sync(
+        &self,
+        _peer: libp2p::PeerId,
+        payload: ChannelSyncPayload,
+    ) -> NetworkResult<ChannelSyncResponsePayload> {
+        self.inject("send_channel_sync").await?;
+        let inner = self.inner.lock().unwrap();
+        inner
+            .channel_sync_responses
+            .get(&payload.channel_id)
+            .cloned()
+            .ok_or_else(|| {
+                NetworkError::Timeout(format!(
+                    "no mock channel sync response configured for channel {}",
+                    payload.channel_id
+                ))
+            })
+    }
+
+    async fn send_invoice(
+        &self,
+        _peer: libp2p::PeerId,
+        payload: InvoicePayload,
+    ) -> NetworkResult<InvoiceAckPayload> {
+        self.inject("send_invoice").await?;
+        self.invoice_ack(&payload.invoice.hash)
+    }
+
+    async fn request_invoice(
+        &self,
+        _peer: libp2p::PeerId,
+        payload: InvoiceRequestPayload,
+    ) -> NetworkResult<Message> {
+        self.inject("request_invoice").await?;
+        let inner = self.inner.lock().unwrap();
+        inner
+            .invoice_responses
+            .get(&payload.invoice_hash)
+            .cloned()
+            .ok_or_else(|| {
+                NetworkError::Timeout(format!(
+                    "no mock invoice response configured for invoice {}",
+                    payload.invoice_hash
+                ))
+            })
+    }
+
+    async fn send_invoice_payment(
+        &self,
+        _peer: libp2p::PeerId,
+        payload: InvoicePayPayload,
+    ) -> NetworkResult<InvoiceAckPayload> {
+        self.inject("send_invoice_payment").await?;
+        self.invoice_ack(&payload.invoice_hash)
+    }
+
+    async fn send_usage_report(
+        &self,
+        peer: libp2p::PeerId,
+        payload: UsageReportPayload,
+    ) -> NetworkResult<UsageReportAckPayload> {
+        self.inject("send_usage_report").await?;
+        let ack = UsageReportAckPayload {
+            hash: payload.hash,
+            accepted: true,
+            reason: None,
+        };
+        self.inner
+            .lock()
+            .unwrap()
+            .usage_reports
+            .push((peer, payload));
+        Ok(ack)
+    }
+
+    async fn send_relay_query(
+        &self,
+        peer: libp2p::PeerId,
+        payload: RelayQueryPayload,
+    ) -> NetworkResult<RelayResponsePayload> {
+        self.inject("send_relay_query").await?;
+        self.inner
+            .lock()
+            .unwrap()
+            .relay_queries
+            .push((peer, payload));
+        Err(NetworkError::Timeout(format!(
+            "no mock relay response from {}",
+            peer
+        )))
+    }
+
+    async fn request_chunk(
+        &self,
+        peer: libp2p::PeerId,
+        payload: ChunkRequestPayload,
+    ) -> NetworkResult<ChunkResponsePayload> {
+        self.inject("request_chunk").await?;
+        let mut inner = self.inner.lock().unwrap();
+        inner.chunk_requests.push((peer, payload.clone()));
+        let chunk = inner
+            .chunk_sources
+            .get(&(peer, payload.hash))
+            .and_then(|content| {
+                content
+                    .chunks(CHUNK_SIZE as usize)
+                    .nth(payload.index as usize)
+            })
+            .map(<[u8]>::to_vec);
+        match chunk {
+            Some(data) => Ok(ChunkResponsePayload {
+                hash: payload.hash,
+                index: payload.index,
+                data,
+            }),
+            None => Err(NetworkError::QueryError {
+                code: ErrorCode::NotFound,
+                message: format!("no mock chunk {} from {}", payload.index, peer),
+                retry_after_ms: None,
+                alternative_providers: Vec::new(),
+            }),
+        }
+    }
+
+    async fn send_ping(
+        &self,
+        peer: libp2p::PeerId,
+        payload: PingPayload,
+    ) -> NetworkResult<PongPayload> {
+        self.inject("send_ping").await?;
+        let offset = self
+            .inner
+            .lock()
+            .unwrap()
+            .peer_clock_offsets
+            .get(&peer)
+            .copied();
+        let offset =
+            offset.ok_or_else(|| NetworkError::Timeout(format!("no mock pong from {}", peer)))?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        Ok(PongPayload {
+            nonce: payload.nonce,
+            timestamp: now.saturating_add_signed(offset),
+        })
+    }
+
+    async fn send_peer_info(
+        &self,
+        peer: libp2p::PeerId,
+        payload: PeerInfoPayload,
+    ) -> NetworkResult<PeerInfoPayload> {
+        self.inject("send_peer_info").await?;
+        let mut inner = self.inner.lock().unwrap();
+        inner.sent_peer_infos.push((peer, payload));
+        inner
+            .peer_infos
+            .get(&peer)
+            .cloned()
+            .ok_or_else(|| NetworkError::Timeout(format!("no mock peer info from {}", peer)))
+    }
+
+    async fn request_group(
+        &self,
+        _peer: libp2p::PeerId,
+        payload: GroupRequestPayload,
+    ) -> NetworkResult<GroupResponsePayload> {
+        self.inject("request_group").await?;
+        let mut inner = self.inner.lock().unwrap();
+        inner.group_requests.push(payload.group_id);
+        Ok(GroupResponsePayload {
+            group: inner.groups.get(&payload.group_id).cloned(),
+        })
+    }
+
+    async fn request_snapshot(
+        &self,
+        peer: libp2p::PeerId,
+        _payload: SnapshotRequestPayload,
+    ) -> NetworkResult<SnapshotResponsePayload> {
+        self.inject("request_snapshot").await?;
+        Ok(SnapshotResponsePayload {
+            snapshot: self.inner.lock().unwrap().snapshots.get(&peer).cloned(),
+        })
+    }
+
+    async fn push_sync_bundle(
+        &self,
+        _peer: libp2p::PeerId,
+        payload: SyncPushPayload,
+    ) -> NetworkResult<SyncPushAckPayload> {
+        self.inject("push_sync_bundle").await?;
+        let mut inner = self.inner.lock().unwrap();
+        let bundle = payload.bundle;
+        let newer = inner
+            .sync_bundles
+            .get(&bundle.owner)
+            .is_none_or(|held| bundle.created_at > held.created_at);
+        if newer {
+            inner.sync_bundles.insert(bundle.owner, bundle);
+        }
+        Ok(SyncPushAckPayload {
+            stored: newer,
+            reason: (!newer).then(|| "a newer bundle is held".to_string()),
+        })
+    }
+
+    async fn pull_sync_bundle(
+        &self,
+        _peer: libp2p::PeerId,
+        payload: SyncPullPayload,
+    ) -> NetworkResult<SyncPullResponsePayload> {
+        self.inject("pull_sync_bundle").await?;
+        Ok(SyncPullResponsePayload {
+            bundle: self
+                .inner
+                .lock()
+                .unwrap()
+                .sync_bundles
+                .get(&payload.owner)
+                .cloned(),
+        })
+    }
+
+    async fn send_availability_check(
+        &self,
+        peer: libp2p::PeerId,
+        payload: AvailabilityCheckPayload,
+    ) -> NetworkResult<AvailabilityResultPayload> {
+        self.inject("send_availability_check").await?;
+        let mut inner = self.inner.lock().unwrap();
+        let result = inner.availability_results.get(&payload.hash).cloned();
+        inner.availability_checks.push((peer, payload));
+        result.ok_or_else(|| {
+            NetworkError::Timeout(format!("no mock availability result from {}", peer))
+        })
+    }
+
     async fn broadcast_settlement_confirm(
         &self,
         _payload: SettleConfirmPayload,
@@ -389,6 +1112,30 @@ impl Network for MockNetwork {
         Ok(())
     }
 
+    async fn broadcast_tag_announce(
+        &self,
+        _tag: &str,
+        _payload: AnnouncePayload,
+    ) -> NetworkResult<()> {
+        Ok(())
+    }
+
+    async fn broadcast_tombstone(&self, _payload: TombstonePayload) -> NetworkResult<()> {
+        Ok(())
+    }
+
+    async fn broadcast_report(&self, _payload: ReportPayload) -> NetworkResult<()> {
+        Ok(())
+    }
+
+    async fn broadcast_revocation(&self, _payload: RevocationPayload) -> NetworkResult<()> {
+        Ok(())
+    }
+
+    async fn broadcast_fraud_proof(&self, _payload: FraudProofPayload) -> NetworkResult<()> {
+        Ok(())
+    }
+
     // =========================================================================
     // Peer Management
     // =========================================================================
@@ -401,7 +1148,8 @@ impl Network for MockNetwork {
         self.inner.lock().unwrap().listen_addresses.clone()
     }
 
-    async fn dial(&self, _addr: Multiaddr) -> NetworkResult<()> {
+    async fn dial(&self, addr: Multiaddr) -> NetworkResult<()> {
+        self.inner.lock().unwrap().dialed.push(addr);
         Ok(())
     }
 
@@ -476,6 +1224,18 @@ impl Network for MockNetwork {
         inner.nodalync_to_libp2p.insert(nodalync_peer, libp2p_peer);
         inner.libp2p_to_nodalync.insert(libp2p_peer, nodalync_peer);
     }
+
+    fn relay_config(&self) -> RelayConfig {
+        self.inner.lock().unwrap().relay.clone()
+    }
+
+    fn set_clock_offset(&self, offset_ms: i64) {
+        self.inner.lock().unwrap().clock_offset = offset_ms;
+    }
+
+    fn set_broadcast_backpressure(&self, engaged: bool) {
+        self.inner.lock().unwrap().broadcast_backpressure = engaged;
+    }
 }
 
 #[cfg(test)]
@@ -495,6 +1255,9 @@ mod tests {
             price: 100,
             addresses: vec![],
             publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
         };
 
         net.dht_announce(hash, payload.clone()).await.unwrap();
@@ -515,6 +1278,9 @@ mod tests {
             price: 0,
             addresses: vec![],
             publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
         };
 
         net.dht_announce(hash, payload).await.unwrap();
@@ -607,6 +1373,73 @@ mod tests {
         assert_eq!(net2.nodalync_peer_id(&peer), Some(nodalync_peer));
     }
 
+    fn ping() -> Message {
+        Message::new(
+            1,
+            MessageType::Ping,
+            Hash([0u8; 32]),
+            0,
+            NodalyncPeerId([0u8; 20]),
+            vec![],
+            nodalync_crypto::Signature::from_bytes([0u8; 64]),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_fault_injection_drops_requests() {
+        let faults = FaultInjector::new(11).with_drop_rate(1.0);
+        let net = MockNetwork::new().with_fault_injector(faults.clone());
+
+        let result = net.send(libp2p::PeerId::random(), ping()).await;
+        assert!(matches!(result, Err(NetworkError::Timeout(_))));
+        assert_eq!(net.sent_message_count(), 0);
+
+        // Dropped broadcasts are lost silently
+        net.broadcast(ping()).await.unwrap();
+        assert_eq!(net.broadcast_message_count(), 0);
+        assert_eq!(faults.stats().dropped, 2);
+
+        faults.set_enabled(false);
+        net.send(libp2p::PeerId::random(), ping()).await.unwrap();
+        assert_eq!(net.sent_message_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fault_injection_duplicates_and_reorders_events() {
+        let faults = FaultInjector::new(11).with_duplicate_rate(1.0);
+        let net = MockNetwork::new().with_fault_injector(faults);
+        let peer = libp2p::PeerId::random();
+        net.enqueue_event(NetworkEvent::PeerConnected { peer });
+
+        assert!(net.next_event().await.is_ok());
+        assert!(net.next_event().await.is_ok());
+        assert!(net.next_event().await.is_err());
+
+        let faults = FaultInjector::new(11).with_reorder_rate(1.0);
+        let net = MockNetwork::new().with_fault_injector(faults);
+        net.enqueue_event(NetworkEvent::PeerConnected { peer });
+        net.enqueue_event(NetworkEvent::PeerDisconnected { peer });
+
+        let first = net.next_event().await.unwrap();
+        assert!(matches!(first, NetworkEvent::PeerDisconnected { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_fault_injection_is_deterministic() {
+        async fn delivered(seed: u64) -> usize {
+            let faults = FaultInjector::new(seed)
+                .with_drop_rate(0.3)
+                .with_duplicate_rate(0.3);
+            let net = MockNetwork::new().with_fault_injector(faults);
+            for _ in 0..50 {
+                let _ = net.send(libp2p::PeerId::random(), ping()).await;
+            }
+            net.sent_message_count()
+        }
+
+        assert_eq!(delivered(99).await, delivered(99).await);
+    }
+
     #[test]
     fn test_clear_messages() {
         let net = MockNetwork::new();
@@ -0,0 +1,897 @@
+//! Mock implementation of the `Operations` trait for testing.
+//!
+//! Records every operation call and answers from scripted responses, so
+//! code written against `Operations` can be unit-tested without a
+//! `NodeState`, network, or settlement layer.
+//!
+//! Without a script, operations behave like a minimal in-memory node:
+//! created content gets a manifest that later operations can find,
+//! channels are tracked per peer, and anything unknown returns the same
+//! "not found" errors as the real implementation.
+//!
+//! ```ignore
+//! let mut ops = MockOperations::new()
+//!     .with_preview(hash, preview)
+//!     .with_error(OperationKind::Query, OpsError::PaymentInsufficient);
+//!
+//! assert!(ops.query(&hash, 100, None).await.is_err());
+//! assert_eq!(ops.call_count(OperationKind::Query), 1);
+//! ```
+
+use async_trait::async_trait;
+use nodalync_crypto::{content_hash, Hash, PeerId, Timestamp};
+use nodalync_ops::{
+    ChannelOps, ContentOps, OpsContext, OpsError, OpsResult, PreviewResponse, PublishOps, QueryOps,
+    QueryResponse, SettlementOps,
+};
+use nodalync_types::{
+    AccessControl, Amount, Channel, L1Summary, L2BuildConfig, L2MergeConfig, Manifest, Metadata,
+    Payment, Version, Visibility,
+};
+use nodalync_wire::{VersionInfo, VersionSpec};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, RwLock};
+
+/// The kind of an operation, used to script errors and count calls.
+///
+/// One variant per async `Operations` method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperationKind {
+    Create,
+    ExtractL1,
+    Publish,
+    Unpublish,
+    Update,
+    Derive,
+    ReferenceL3AsL0,
+    Preview,
+    Query,
+    GetVersions,
+    SetVisibility,
+    SetAccess,
+    OpenChannel,
+    AcceptChannel,
+    UpdateChannel,
+    CloseChannel,
+    DisputeChannel,
+    TriggerSettlement,
+    BuildL2,
+    MergeL2,
+}
+
+/// A recorded call to an operation, with its arguments.
+///
+/// Fields mirror the parameters of the corresponding `Operations` method.
+#[derive(Debug, Clone)]
+pub enum OperationCall {
+    Create {
+        content: Vec<u8>,
+        metadata: Metadata,
+    },
+    ExtractL1 {
+        hash: Hash,
+    },
+    Publish {
+        hash: Hash,
+        visibility: Visibility,
+        price: Amount,
+    },
+    Unpublish {
+        hash: Hash,
+    },
+    Update {
+        old_hash: Hash,
+        new_content: Vec<u8>,
+        new_metadata: Metadata,
+    },
+    Derive {
+        sources: Vec<Hash>,
+        insight: Vec<u8>,
+        metadata: Metadata,
+    },
+    ReferenceL3AsL0 {
+        l3_hash: Hash,
+    },
+    Preview {
+        hash: Hash,
+    },
+    Query {
+        hash: Hash,
+        payment_amount: Amount,
+        version: Option<VersionSpec>,
+    },
+    GetVersions {
+        root_hash: Hash,
+    },
+    SetVisibility {
+        hash: Hash,
+        visibility: Visibility,
+    },
+    SetAccess {
+        hash: Hash,
+        access: AccessControl,
+    },
+    OpenChannel {
+        peer: PeerId,
+        deposit: Amount,
+    },
+    AcceptChannel {
+        channel_id: Hash,
+        peer: PeerId,
+        their_deposit: Amount,
+        my_deposit: Amount,
+    },
+    UpdateChannel {
+        peer: PeerId,
+        payment: Payment,
+    },
+    CloseChannel {
+        peer: PeerId,
+    },
+    DisputeChannel {
+        peer: PeerId,
+    },
+    TriggerSettlement,
+    BuildL2 {
+        source_l1_hashes: Vec<Hash>,
+        config: Option<L2BuildConfig>,
+    },
+    MergeL2 {
+        source_l2_hashes: Vec<Hash>,
+        config: Option<L2MergeConfig>,
+    },
+}
+
+impl OperationCall {
+    /// Get the kind of this call.
+    pub fn kind(&self) -> OperationKind {
+        match self {
+            Self::Create { .. } => OperationKind::Create,
+            Self::ExtractL1 { .. } => OperationKind::ExtractL1,
+            Self::Publish { .. } => OperationKind::Publish,
+            Self::Unpublish { .. } => OperationKind::Unpublish,
+            Self::Update { .. } => OperationKind::Update,
+            Self::Derive { .. } => OperationKind::Derive,
+            Self::ReferenceL3AsL0 { .. } => OperationKind::ReferenceL3AsL0,
+            Self::Preview { .. } => OperationKind::Preview,
+            Self::Query { .. } => OperationKind::Query,
+            Self::GetVersions { .. } => OperationKind::GetVersions,
+            Self::SetVisibility { .. } => OperationKind::SetVisibility,
+            Self::SetAccess { .. } => OperationKind::SetAccess,
+            Self::OpenChannel { .. } => OperationKind::OpenChannel,
+            Self::AcceptChannel { .. } => OperationKind::AcceptChannel,
+            Self::UpdateChannel { .. } => OperationKind::UpdateChannel,
+            Self::CloseChannel { .. } => OperationKind::CloseChannel,
+            Self::DisputeChannel { .. } => OperationKind::DisputeChannel,
+            Self::TriggerSettlement => OperationKind::TriggerSettlement,
+            Self::BuildL2 { .. } => OperationKind::BuildL2,
+            Self::MergeL2 { .. } => OperationKind::MergeL2,
+        }
+    }
+}
+
+struct MockOperationsInner {
+    /// Own peer ID.
+    peer_id: PeerId,
+    /// Time returned by `now()`.
+    now: Timestamp,
+    /// Every call made, in order.
+    calls: Vec<OperationCall>,
+    /// Errors returned by the next calls of each kind, in order.
+    errors: HashMap<OperationKind, VecDeque<OpsError>>,
+    /// Known manifests (created locally or scripted).
+    manifests: HashMap<Hash, Manifest>,
+    /// Scripted preview responses.
+    previews: HashMap<Hash, PreviewResponse>,
+    /// Scripted query responses.
+    query_responses: HashMap<Hash, QueryResponse>,
+    /// Scripted L1 summaries.
+    l1_summaries: HashMap<Hash, L1Summary>,
+    /// Scripted version histories: root hash -> versions.
+    versions: HashMap<Hash, Vec<VersionInfo>>,
+    /// Channels by peer.
+    channels: HashMap<PeerId, Channel>,
+    /// Content that has been queried.
+    queried: HashSet<Hash>,
+    /// Batch IDs returned by the next settlement triggers, in order.
+    settlement_batches: VecDeque<Hash>,
+}
+
+/// A mock implementation of the `Operations` trait for testing.
+///
+/// Uses `Arc<RwLock<...>>` internally, so it is cheap to clone and all
+/// clones share the same state. A test can hand one clone to the code
+/// under test and keep another for scripting and assertions.
+#[derive(Clone)]
+pub struct MockOperations {
+    inner: Arc<RwLock<MockOperationsInner>>,
+}
+
+impl Default for MockOperations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockOperations {
+    /// Create a new MockOperations with an all-zero peer ID and time.
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(MockOperationsInner {
+                peer_id: PeerId([0u8; 20]),
+                now: 0,
+                calls: Vec::new(),
+                errors: HashMap::new(),
+                manifests: HashMap::new(),
+                previews: HashMap::new(),
+                query_responses: HashMap::new(),
+                l1_summaries: HashMap::new(),
+                versions: HashMap::new(),
+                channels: HashMap::new(),
+                queried: HashSet::new(),
+                settlement_batches: VecDeque::new(),
+            })),
+        }
+    }
+
+    // =========================================================================
+    // Builder Methods
+    // =========================================================================
+
+    /// Set the peer ID returned by `my_peer_id()`.
+    pub fn with_peer_id(self, peer_id: PeerId) -> Self {
+        self.inner.write().unwrap().peer_id = peer_id;
+        self
+    }
+
+    /// Set the time returned by `now()`.
+    pub fn with_time(self, now: Timestamp) -> Self {
+        self.set_time(now);
+        self
+    }
+
+    /// Add a known manifest.
+    pub fn with_manifest(self, manifest: Manifest) -> Self {
+        self.inner
+            .write()
+            .unwrap()
+            .manifests
+            .insert(manifest.hash, manifest);
+        self
+    }
+
+    /// Script the response to `preview(hash)`.
+    pub fn with_preview(self, hash: Hash, response: PreviewResponse) -> Self {
+        self.inner.write().unwrap().previews.insert(hash, response);
+        self
+    }
+
+    /// Script the response to `query(hash, ..)`.
+    pub fn with_query_response(self, hash: Hash, response: QueryResponse) -> Self {
+        self.inner
+            .write()
+            .unwrap()
+            .query_responses
+            .insert(hash, response);
+        self
+    }
+
+    /// Script the response to `extract_l1(hash)`.
+    pub fn with_l1_summary(self, hash: Hash, summary: L1Summary) -> Self {
+        self.inner
+            .write()
+            .unwrap()
+            .l1_summaries
+            .insert(hash, summary);
+        self
+    }
+
+    /// Script the response to `get_versions(root_hash)`.
+    pub fn with_versions(self, root_hash: Hash, versions: Vec<VersionInfo>) -> Self {
+        self.inner
+            .write()
+            .unwrap()
+            .versions
+            .insert(root_hash, versions);
+        self
+    }
+
+    /// Add an existing channel.
+    pub fn with_channel(self, channel: Channel) -> Self {
+        self.inner
+            .write()
+            .unwrap()
+            .channels
+            .insert(channel.peer_id, channel);
+        self
+    }
+
+    /// Mark content as already queried.
+    pub fn with_queried(self, hash: Hash) -> Self {
+        self.inner.write().unwrap().queried.insert(hash);
+        self
+    }
+
+    /// Make the next `trigger_settlement()` return this batch ID.
+    ///
+    /// Can be called repeatedly to queue several batches. With nothing
+    /// queued, settlement returns `None` (nothing to settle).
+    pub fn with_settlement_batch(self, batch_id: Hash) -> Self {
+        self.inner
+            .write()
+            .unwrap()
+            .settlement_batches
+            .push_back(batch_id);
+        self
+    }
+
+    /// Make the next call of `kind` fail with `error`.
+    ///
+    /// Can be called repeatedly to queue several errors; each is returned
+    /// once, in order. The failed call is still recorded.
+    pub fn with_error(self, kind: OperationKind, error: OpsError) -> Self {
+        self.fail_next(kind, error);
+        self
+    }
+
+    // =========================================================================
+    // Runtime Control
+    // =========================================================================
+
+    /// Set the time returned by `now()`.
+    pub fn set_time(&self, now: Timestamp) {
+        self.inner.write().unwrap().now = now;
+    }
+
+    /// Make the next call of `kind` fail with `error`.
+    pub fn fail_next(&self, kind: OperationKind, error: OpsError) {
+        self.inner
+            .write()
+            .unwrap()
+            .errors
+            .entry(kind)
+            .or_default()
+            .push_back(error);
+    }
+
+    // =========================================================================
+    // Assertion Helpers
+    // =========================================================================
+
+    /// Get all recorded calls, in order.
+    pub fn calls(&self) -> Vec<OperationCall> {
+        self.inner.read().unwrap().calls.clone()
+    }
+
+    /// Get the recorded calls of one kind, in order.
+    pub fn calls_of(&self, kind: OperationKind) -> Vec<OperationCall> {
+        self.inner
+            .read()
+            .unwrap()
+            .calls
+            .iter()
+            .filter(|c| c.kind() == kind)
+            .cloned()
+            .collect()
+    }
+
+    /// Get the number of calls of one kind.
+    pub fn call_count(&self, kind: OperationKind) -> usize {
+        self.inner
+            .read()
+            .unwrap()
+            .calls
+            .iter()
+            .filter(|c| c.kind() == kind)
+            .count()
+    }
+
+    /// Get the most recent call, if any.
+    pub fn last_call(&self) -> Option<OperationCall> {
+        self.inner.read().unwrap().calls.last().cloned()
+    }
+
+    /// Clear recorded calls (keeps scripted responses and state).
+    pub fn clear_calls(&self) {
+        self.inner.write().unwrap().calls.clear();
+    }
+
+    /// Get all known manifests.
+    pub fn manifests(&self) -> HashMap<Hash, Manifest> {
+        self.inner.read().unwrap().manifests.clone()
+    }
+
+    /// Get the channel with a peer, if any.
+    pub fn channel(&self, peer: &PeerId) -> Option<Channel> {
+        self.inner.read().unwrap().channels.get(peer).cloned()
+    }
+
+    // =========================================================================
+    // Internal
+    // =========================================================================
+
+    /// Record a call and return its scripted error, if any.
+    fn record(&self, call: OperationCall) -> OpsResult<()> {
+        let mut inner = self.inner.write().unwrap();
+        let kind = call.kind();
+        inner.calls.push(call);
+        match inner.errors.get_mut(&kind).and_then(VecDeque::pop_front) {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Apply a change to a known manifest.
+    fn modify_manifest(&self, hash: &Hash, f: impl FnOnce(&mut Manifest)) -> OpsResult<()> {
+        let mut inner = self.inner.write().unwrap();
+        let manifest = inner
+            .manifests
+            .get_mut(hash)
+            .ok_or(OpsError::ManifestNotFound(*hash))?;
+        f(manifest);
+        Ok(())
+    }
+
+    /// Apply a change to the channel with a peer.
+    fn modify_channel(
+        &self,
+        peer: &PeerId,
+        f: impl FnOnce(&mut Channel, Timestamp),
+    ) -> OpsResult<()> {
+        let mut inner = self.inner.write().unwrap();
+        let now = inner.now;
+        let channel = inner
+            .channels
+            .get_mut(peer)
+            .ok_or(OpsError::ChannelNotFound)?;
+        f(channel, now);
+        Ok(())
+    }
+}
+
+/// Deterministic hash over a list of hashes.
+fn combined_hash(hashes: &[Hash]) -> Hash {
+    let bytes: Vec<u8> = hashes.iter().flat_map(|h| h.0).collect();
+    content_hash(&bytes)
+}
+
+impl OpsContext for MockOperations {
+    fn my_peer_id(&self) -> PeerId {
+        self.inner.read().unwrap().peer_id
+    }
+
+    fn now(&self) -> Timestamp {
+        self.inner.read().unwrap().now
+    }
+}
+
+#[async_trait]
+impl ContentOps for MockOperations {
+    async fn create(&self, content: &[u8], metadata: Metadata) -> OpsResult<Hash> {
+        self.record(OperationCall::Create {
+            content: content.to_vec(),
+            metadata: metadata.clone(),
+        })?;
+        let hash = content_hash(content);
+        let mut inner = self.inner.write().unwrap();
+        let manifest = Manifest::new_l0(hash, inner.peer_id, metadata, inner.now);
+        inner.manifests.insert(hash, manifest);
+        Ok(hash)
+    }
+
+    async fn extract_l1(&self, hash: &Hash) -> OpsResult<L1Summary> {
+        self.record(OperationCall::ExtractL1 { hash: *hash })?;
+        let inner = self.inner.read().unwrap();
+        if let Some(summary) = inner.l1_summaries.get(hash) {
+            return Ok(summary.clone());
+        }
+        if inner.manifests.contains_key(hash) {
+            return Ok(L1Summary::empty(*hash));
+        }
+        Err(OpsError::NotFound(*hash))
+    }
+
+    async fn update(
+        &self,
+        old_hash: &Hash,
+        new_content: &[u8],
+        new_metadata: Metadata,
+    ) -> OpsResult<Hash> {
+        self.record(OperationCall::Update {
+            old_hash: *old_hash,
+            new_content: new_content.to_vec(),
+            new_metadata: new_metadata.clone(),
+        })?;
+        let hash = content_hash(new_content);
+        let mut inner = self.inner.write().unwrap();
+        let old = inner
+            .manifests
+            .get(old_hash)
+            .cloned()
+            .ok_or(OpsError::ManifestNotFound(*old_hash))?;
+        let mut manifest = Manifest::new_l0(hash, old.owner, new_metadata, inner.now);
+        manifest.version = Version::new_from_previous(&old.version, old.hash, inner.now);
+        manifest.visibility = old.visibility;
+        inner.manifests.insert(hash, manifest);
+        Ok(hash)
+    }
+
+    async fn derive(
+        &self,
+        sources: &[Hash],
+        insight: &[u8],
+        metadata: Metadata,
+    ) -> OpsResult<Hash> {
+        self.record(OperationCall::Derive {
+            sources: sources.to_vec(),
+            insight: insight.to_vec(),
+            metadata,
+        })?;
+        let inner = self.inner.read().unwrap();
+        if let Some(source) = sources.iter().find(|s| !inner.queried.contains(s)) {
+            return Err(OpsError::SourceNotQueried(*source));
+        }
+        Ok(content_hash(insight))
+    }
+
+    async fn reference_l3_as_l0(&self, l3_hash: &Hash) -> OpsResult<Hash> {
+        self.record(OperationCall::ReferenceL3AsL0 { l3_hash: *l3_hash })?;
+        if !self.inner.read().unwrap().queried.contains(l3_hash) {
+            return Err(OpsError::SourceNotQueried(*l3_hash));
+        }
+        Ok(*l3_hash)
+    }
+
+    async fn build_l2(
+        &self,
+        source_l1_hashes: Vec<Hash>,
+        config: Option<L2BuildConfig>,
+    ) -> OpsResult<Hash> {
+        let hash = combined_hash(&source_l1_hashes);
+        self.record(OperationCall::BuildL2 {
+            source_l1_hashes,
+            config,
+        })?;
+        Ok(hash)
+    }
+
+    async fn merge_l2(
+        &self,
+        source_l2_hashes: Vec<Hash>,
+        config: Option<L2MergeConfig>,
+    ) -> OpsResult<Hash> {
+        let hash = combined_hash(&source_l2_hashes);
+        self.record(OperationCall::MergeL2 {
+            source_l2_hashes,
+            config,
+        })?;
+        Ok(hash)
+    }
+
+    fn get_manifest(&self, hash: &Hash) -> OpsResult<Option<Manifest>> {
+        Ok(self.inner.read().unwrap().manifests.get(hash).cloned())
+    }
+}
+
+#[async_trait]
+impl PublishOps for MockOperations {
+    async fn publish(&self, hash: &Hash, visibility: Visibility, price: Amount) -> OpsResult<()> {
+        self.record(OperationCall::Publish {
+            hash: *hash,
+            visibility,
+            price,
+        })?;
+        self.modify_manifest(hash, |m| {
+            m.visibility = visibility;
+            m.economics.price = price;
+        })
+    }
+
+    async fn unpublish(&self, hash: &Hash) -> OpsResult<()> {
+        self.record(OperationCall::Unpublish { hash: *hash })?;
+        self.modify_manifest(hash, |m| m.visibility = Visibility::Private)
+    }
+
+    async fn set_visibility(&self, hash: &Hash, visibility: Visibility) -> OpsResult<()> {
+        self.record(OperationCall::SetVisibility {
+            hash: *hash,
+            visibility,
+        })?;
+        self.modify_manifest(hash, |m| m.visibility = visibility)
+    }
+
+    async fn set_access(&self, hash: &Hash, access: AccessControl) -> OpsResult<()> {
+        self.record(OperationCall::SetAccess {
+            hash: *hash,
+            access: access.clone(),
+        })?;
+        self.modify_manifest(hash, |m| m.access = access)
+    }
+}
+
+#[async_trait]
+impl QueryOps for MockOperations {
+    async fn preview(&self, hash: &Hash) -> OpsResult<PreviewResponse> {
+        self.record(OperationCall::Preview { hash: *hash })?;
+        let inner = self.inner.read().unwrap();
+        if let Some(response) = inner.previews.get(hash) {
+            return Ok(response.clone());
+        }
+        match inner.manifests.get(hash) {
+            Some(manifest) => Ok(PreviewResponse {
+                manifest: manifest.clone(),
+                l1_summary: inner
+                    .l1_summaries
+                    .get(hash)
+                    .cloned()
+                    .unwrap_or_else(|| L1Summary::empty(*hash)),
+                provider_peer_id: None,
+                collection: None,
+                forks: Vec::new(),
+                window_queries: 0,
+            }),
+            None => Err(OpsError::NotFound(*hash)),
+        }
+    }
+
+    async fn query(
+        &self,
+        hash: &Hash,
+        payment_amount: Amount,
+        version: Option<VersionSpec>,
+    ) -> OpsResult<QueryResponse> {
+        self.record(OperationCall::Query {
+            hash: *hash,
+            payment_amount,
+            version,
+        })?;
+        let mut inner = self.inner.write().unwrap();
+        let response = inner
+            .query_responses
+            .get(hash)
+            .cloned()
+            .ok_or(OpsError::NotFound(*hash))?;
+        if payment_amount < response.manifest.economics.price {
+            return Err(OpsError::PaymentInsufficient);
+        }
+        inner.queried.insert(*hash);
+        Ok(response)
+    }
+
+    async fn get_versions(&self, root_hash: &Hash) -> OpsResult<Vec<VersionInfo>> {
+        self.record(OperationCall::GetVersions {
+            root_hash: *root_hash,
+        })?;
+        Ok(self
+            .inner
+            .read()
+            .unwrap()
+            .versions
+            .get(root_hash)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn was_queried(&self, hash: &Hash) -> bool {
+        self.inner.read().unwrap().queried.contains(hash)
+    }
+}
+
+#[async_trait]
+impl ChannelOps for MockOperations {
+    async fn open_channel(&self, peer: &PeerId, deposit: Amount) -> OpsResult<Channel> {
+        self.record(OperationCall::OpenChannel {
+            peer: *peer,
+            deposit,
+        })?;
+        let mut inner = self.inner.write().unwrap();
+        if inner.channels.get(peer).is_some_and(|c| !c.is_closed()) {
+            return Err(OpsError::ChannelAlreadyExists);
+        }
+        let channel_id = combined_hash(&[content_hash(&inner.peer_id.0), content_hash(&peer.0)]);
+        let mut channel = Channel::new(channel_id, *peer, deposit, inner.now);
+        channel.mark_open(0, inner.now);
+        inner.channels.insert(*peer, channel.clone());
+        Ok(channel)
+    }
+
+    async fn accept_channel(
+        &self,
+        channel_id: &Hash,
+        peer: &PeerId,
+        their_deposit: Amount,
+        my_deposit: Amount,
+    ) -> OpsResult<Channel> {
+        self.record(OperationCall::AcceptChannel {
+            channel_id: *channel_id,
+            peer: *peer,
+            their_deposit,
+            my_deposit,
+        })?;
+        let mut inner = self.inner.write().unwrap();
+        if inner.channels.get(peer).is_some_and(|c| !c.is_closed()) {
+            return Err(OpsError::ChannelAlreadyExists);
+        }
+        let channel = Channel::accepted(*channel_id, *peer, their_deposit, my_deposit, inner.now);
+        inner.channels.insert(*peer, channel.clone());
+        Ok(channel)
+    }
+
+    async fn update_channel(&self, peer: &PeerId, payment: Payment) -> OpsResult<()> {
+        self.record(OperationCall::UpdateChannel {
+            peer: *peer,
+            payment: payment.clone(),
+        })?;
+        let mut result = Ok(());
+        self.modify_channel(peer, |channel, now| {
+            if channel.receive(payment, now).is_err() {
+                result = Err(OpsError::InsufficientChannelBalance);
+            }
+        })?;
+        result
+    }
+
+    async fn close_channel(&self, peer: &PeerId) -> OpsResult<()> {
+        self.record(OperationCall::CloseChannel { peer: *peer })?;
+        self.modify_channel(peer, |channel, now| channel.mark_closed(now))
+    }
+
+    async fn dispute_channel(&self, peer: &PeerId) -> OpsResult<()> {
+        self.record(OperationCall::DisputeChannel { peer: *peer })?;
+        self.modify_channel(peer, |channel, now| channel.mark_disputed(now))
+    }
+}
+
+#[async_trait]
+impl SettlementOps for MockOperations {
+    async fn trigger_settlement(&self) -> OpsResult<Option<Hash>> {
+        self.record(OperationCall::TriggerSettlement)?;
+        Ok(self.inner.write().unwrap().settlement_batches.pop_front())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_wire::PaymentReceipt;
+
+    fn peer(n: u8) -> PeerId {
+        PeerId([n; 20])
+    }
+
+    fn query_response(hash: Hash, price: Amount) -> QueryResponse {
+        let mut manifest = Manifest::new_l0(hash, peer(2), Metadata::new("Remote", 7), 0);
+        manifest.economics.price = price;
+        QueryResponse {
+            content: b"remote content".to_vec(),
+            manifest,
+            receipt: PaymentReceipt {
+                payment_id: hash,
+                amount: price,
+                timestamp: 0,
+                channel_nonce: 1,
+                distributor_signature: nodalync_crypto::Signature([0u8; 64]),
+                app_fee: 0,
+                app_fee_recipient: None,
+                referral_fee: 0,
+                referrer: None,
+            },
+            bundle: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_publish_preview() {
+        let ops = MockOperations::new().with_peer_id(peer(1)).with_time(1000);
+
+        let hash = ops
+            .create(b"hello", Metadata::new("Hello", 5))
+            .await
+            .unwrap();
+        ops.publish(&hash, Visibility::Shared, 50).await.unwrap();
+
+        let preview = ops.preview(&hash).await.unwrap();
+        assert_eq!(preview.manifest.owner, peer(1));
+        assert_eq!(preview.manifest.visibility, Visibility::Shared);
+        assert_eq!(preview.manifest.economics.price, 50);
+
+        let kinds: Vec<_> = ops.calls().iter().map(OperationCall::kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                OperationKind::Create,
+                OperationKind::Publish,
+                OperationKind::Preview
+            ]
+        );
+        assert!(matches!(
+            ops.last_call(),
+            Some(OperationCall::Preview { hash: h }) if h == hash
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_scripted_query_and_derive() {
+        let source = content_hash(b"remote");
+        let ops = MockOperations::new().with_query_response(source, query_response(source, 10));
+
+        // Sources must be queried before deriving
+        let err = ops
+            .derive(&[source], b"insight", Metadata::new("Insight", 7))
+            .await;
+        assert!(matches!(err, Err(OpsError::SourceNotQueried(_))));
+
+        assert!(matches!(
+            ops.query(&source, 5, None).await,
+            Err(OpsError::PaymentInsufficient)
+        ));
+        let response = ops.query(&source, 10, None).await.unwrap();
+        assert_eq!(response.content, b"remote content");
+        assert!(ops.was_queried(&source));
+
+        ops.derive(&[source], b"insight", Metadata::new("Insight", 7))
+            .await
+            .unwrap();
+        assert_eq!(ops.call_count(OperationKind::Query), 2);
+        assert_eq!(ops.call_count(OperationKind::Derive), 2);
+    }
+
+    #[tokio::test]
+    async fn test_scripted_errors_are_consumed_in_order() {
+        let ops = MockOperations::new()
+            .with_error(
+                OperationKind::TriggerSettlement,
+                OpsError::SettlementRequired,
+            )
+            .with_settlement_batch(content_hash(b"batch"));
+
+        assert!(matches!(
+            ops.trigger_settlement().await,
+            Err(OpsError::SettlementRequired)
+        ));
+        assert_eq!(
+            ops.trigger_settlement().await.unwrap(),
+            Some(content_hash(b"batch"))
+        );
+        assert_eq!(ops.trigger_settlement().await.unwrap(), None);
+        assert_eq!(ops.call_count(OperationKind::TriggerSettlement), 3);
+    }
+
+    #[tokio::test]
+    async fn test_channels_and_shared_state() {
+        let ops = MockOperations::new();
+        let observer = ops.clone();
+
+        let channel = ops.open_channel(&peer(3), 100).await.unwrap();
+        assert!(channel.is_open());
+        assert!(matches!(
+            ops.open_channel(&peer(3), 100).await,
+            Err(OpsError::ChannelAlreadyExists)
+        ));
+
+        ops.close_channel(&peer(3)).await.unwrap();
+        assert!(observer.channel(&peer(3)).unwrap().is_closed());
+        assert!(matches!(
+            ops.dispute_channel(&peer(4)).await,
+            Err(OpsError::ChannelNotFound)
+        ));
+        assert_eq!(observer.calls_of(OperationKind::OpenChannel).len(), 2);
+
+        observer.clear_calls();
+        assert!(ops.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_usable_as_combined_operations() {
+        let ops = MockOperations::new().with_peer_id(peer(5));
+        let combined: &dyn nodalync_ops::Operations = &ops;
+
+        assert_eq!(combined.my_peer_id(), peer(5));
+        let hash = combined
+            .create(b"combined", Metadata::new("Combined", 8))
+            .await
+            .unwrap();
+        assert!(combined.get_manifest(&hash).unwrap().is_some());
+        assert!(!combined.was_queried(&hash));
+    }
+}
@@ -1,19 +1,22 @@
 //! Mock implementation of the `Settlement` trait for testing.
 //!
 //! Provides a configurable mock settlement layer that tracks deposits,
-//! withdrawals, channels, and attestations in memory.
+//! withdrawals, channels, attestations, and bonds in memory. An optional
+//! [`FaultInjector`] adds latency and transient failures.
 
 use async_trait::async_trait;
 use nodalync_crypto::{Hash, PeerId, Signature};
 use nodalync_settle::{
-    AccountId, Attestation, ChannelId, SettleError, SettleResult, Settlement, SettlementStatus,
-    TransactionId,
+    AccountId, Attestation, ChannelId, OnChainDispute, SettleError, SettleResult, Settlement,
+    SettlementStatus, TransactionId,
 };
-use nodalync_types::SettlementBatch;
+use nodalync_types::{PendingDispute, SettlementBatch};
 use nodalync_wire::{ChannelBalances, ChannelUpdatePayload};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
+use crate::fault::{FaultInjector, SettlementFault};
+
 struct MockSettlementInner {
     /// Contract balance (deposited into settlement contract).
     balance: u64,
@@ -25,8 +28,16 @@ struct MockSettlementInner {
     withdrawals: Vec<u64>,
     /// Open channels: channel_id_string -> (peer, deposit).
     channels: HashMap<String, (PeerId, u64)>,
+    /// Open disputes: channel_id_string -> highest state submitted.
+    disputes: HashMap<String, OnChainDispute>,
     /// Stored attestations: content_hash -> Attestation.
     attestations: HashMap<Hash, Attestation>,
+    /// Publisher bonds: account -> bonded amount.
+    bonds: HashMap<AccountId, u64>,
+    /// Record of all slashes: (account, amount slashed, evidence).
+    slashes: Vec<(AccountId, u64, Hash)>,
+    /// Fraud proofs recorded on-chain: proof hash -> provider.
+    fraud_proofs: HashMap<Hash, AccountId>,
     /// Record of all settled batches.
     settled_batches: Vec<SettlementBatch>,
     /// Peer -> AccountId mappings.
@@ -37,6 +48,8 @@ struct MockSettlementInner {
     should_fail: bool,
     /// Auto-incrementing transaction counter.
     tx_counter: u64,
+    /// Optional fault injector applied to every operation.
+    faults: Option<FaultInjector>,
 }
 
 /// A mock implementation of the `Settlement` trait for testing.
@@ -65,12 +78,17 @@ impl MockSettlement {
                 deposits: Vec::new(),
                 withdrawals: Vec::new(),
                 channels: HashMap::new(),
+                disputes: HashMap::new(),
                 attestations: HashMap::new(),
+                bonds: HashMap::new(),
+                slashes: Vec::new(),
+                fraud_proofs: HashMap::new(),
                 settled_batches: Vec::new(),
                 peer_accounts: HashMap::new(),
                 own_account: AccountId::simple(99999),
                 should_fail: false,
                 tx_counter: 0,
+                faults: None,
             })),
         }
     }
@@ -105,6 +123,16 @@ impl MockSettlement {
         self.inner.write().unwrap().should_fail = should_fail;
     }
 
+    /// Attach a fault injector.
+    ///
+    /// Operations may then be delayed, fail with a retryable
+    /// `SettleError::Network` without taking effect, or take effect and
+    /// fail with `SettleError::Timeout` (a lost confirmation).
+    pub fn with_fault_injector(self, faults: FaultInjector) -> Self {
+        self.inner.write().unwrap().faults = Some(faults);
+        self
+    }
+
     // =========================================================================
     // Assertion Helpers
     // =========================================================================
@@ -139,6 +167,54 @@ impl MockSettlement {
         self.inner.read().unwrap().attestations.len()
     }
 
+    /// Set an account's bond directly.
+    pub fn set_bond(&self, account: AccountId, amount: u64) {
+        self.inner.write().unwrap().bonds.insert(account, amount);
+    }
+
+    /// Get all slashes made, as (account, amount slashed, evidence).
+    pub fn slashes(&self) -> Vec<(AccountId, u64, Hash)> {
+        self.inner.read().unwrap().slashes.clone()
+    }
+
+    /// Get the provider a fraud proof was recorded against, if any.
+    pub fn fraud_proof(&self, proof_hash: &Hash) -> Option<AccountId> {
+        self.inner
+            .read()
+            .unwrap()
+            .fraud_proofs
+            .get(proof_hash)
+            .copied()
+    }
+
+    /// Apply injected latency and failures before an operation.
+    ///
+    /// Returns the fault to apply once the operation has taken effect.
+    async fn inject(&self) -> SettleResult<Option<SettlementFault>> {
+        let faults = self.inner.read().unwrap().faults.clone();
+        let Some(faults) = faults else {
+            return Ok(None);
+        };
+        faults.delay().await;
+        match faults.settlement_fault() {
+            Some(SettlementFault::Fail) => {
+                Err(SettleError::network("mock: injected transient failure"))
+            }
+            fault => Ok(fault),
+        }
+    }
+
+    /// Report a completed operation, replacing success with an error if its
+    /// confirmation is to be lost.
+    fn confirm<T>(result: SettleResult<T>, fault: Option<SettlementFault>) -> SettleResult<T> {
+        match (result, fault) {
+            (Ok(_), Some(SettlementFault::LostAck)) => {
+                Err(SettleError::timeout("mock: injected lost confirmation"))
+            }
+            (result, _) => result,
+        }
+    }
+
     /// Generate the next transaction ID.
     fn next_tx_id(inner: &mut MockSettlementInner) -> TransactionId {
         inner.tx_counter += 1;
@@ -153,42 +229,58 @@ impl Settlement for MockSettlement {
     // =========================================================================
 
     async fn deposit(&self, amount: u64) -> SettleResult<TransactionId> {
-        let mut inner = self.inner.write().unwrap();
-        if inner.should_fail {
-            return Err(SettleError::transaction_failed("mock: configured to fail"));
-        }
-        inner.balance += amount;
-        inner.deposits.push(amount);
-        Ok(Self::next_tx_id(&mut inner))
+        let fault = self.inject().await?;
+        let result = {
+            let mut inner = self.inner.write().unwrap();
+            if inner.should_fail {
+                return Err(SettleError::transaction_failed("mock: configured to fail"));
+            }
+            inner.balance += amount;
+            inner.deposits.push(amount);
+            Ok(Self::next_tx_id(&mut inner))
+        };
+        Self::confirm(result, fault)
     }
 
     async fn withdraw(&self, amount: u64) -> SettleResult<TransactionId> {
-        let mut inner = self.inner.write().unwrap();
-        if inner.should_fail {
-            return Err(SettleError::transaction_failed("mock: configured to fail"));
-        }
-        if inner.balance < amount {
-            return Err(SettleError::insufficient_balance(inner.balance, amount));
-        }
-        inner.balance -= amount;
-        inner.withdrawals.push(amount);
-        Ok(Self::next_tx_id(&mut inner))
+        let fault = self.inject().await?;
+        let result = {
+            let mut inner = self.inner.write().unwrap();
+            if inner.should_fail {
+                return Err(SettleError::transaction_failed("mock: configured to fail"));
+            }
+            if inner.balance < amount {
+                return Err(SettleError::insufficient_balance(inner.balance, amount));
+            }
+            inner.balance -= amount;
+            inner.withdrawals.push(amount);
+            Ok(Self::next_tx_id(&mut inner))
+        };
+        Self::confirm(result, fault)
     }
 
     async fn get_balance(&self) -> SettleResult<u64> {
-        let inner = self.inner.read().unwrap();
-        if inner.should_fail {
-            return Err(SettleError::transaction_failed("mock: configured to fail"));
-        }
-        Ok(inner.balance)
+        let fault = self.inject().await?;
+        let result = {
+            let inner = self.inner.read().unwrap();
+            if inner.should_fail {
+                return Err(SettleError::transaction_failed("mock: configured to fail"));
+            }
+            Ok(inner.balance)
+        };
+        Self::confirm(result, fault)
     }
 
     async fn get_account_balance(&self) -> SettleResult<u64> {
-        let inner = self.inner.read().unwrap();
-        if inner.should_fail {
-            return Err(SettleError::transaction_failed("mock: configured to fail"));
-        }
-        Ok(inner.account_balance)
+        let fault = self.inject().await?;
+        let result = {
+            let inner = self.inner.read().unwrap();
+            if inner.should_fail {
+                return Err(SettleError::transaction_failed("mock: configured to fail"));
+            }
+            Ok(inner.account_balance)
+        };
+        Self::confirm(result, fault)
     }
 
     // =========================================================================
@@ -200,29 +292,114 @@ impl Settlement for MockSettlement {
         content_hash: &Hash,
         provenance_root: &Hash,
     ) -> SettleResult<TransactionId> {
-        let mut inner = self.inner.write().unwrap();
-        if inner.should_fail {
-            return Err(SettleError::transaction_failed("mock: configured to fail"));
-        }
-        let attestation = Attestation::new(
-            *content_hash,
-            inner.own_account,
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64,
-            *provenance_root,
-        );
-        inner.attestations.insert(*content_hash, attestation);
-        Ok(Self::next_tx_id(&mut inner))
+        let fault = self.inject().await?;
+        let result = {
+            let mut inner = self.inner.write().unwrap();
+            if inner.should_fail {
+                return Err(SettleError::transaction_failed("mock: configured to fail"));
+            }
+            let attestation = Attestation::new(
+                *content_hash,
+                inner.own_account,
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_millis() as u64,
+                *provenance_root,
+            );
+            inner.attestations.insert(*content_hash, attestation);
+            Ok(Self::next_tx_id(&mut inner))
+        };
+        Self::confirm(result, fault)
     }
 
     async fn get_attestation(&self, content_hash: &Hash) -> SettleResult<Option<Attestation>> {
-        let inner = self.inner.read().unwrap();
-        if inner.should_fail {
-            return Err(SettleError::transaction_failed("mock: configured to fail"));
-        }
-        Ok(inner.attestations.get(content_hash).cloned())
+        let fault = self.inject().await?;
+        let result = {
+            let inner = self.inner.read().unwrap();
+            if inner.should_fail {
+                return Err(SettleError::transaction_failed("mock: configured to fail"));
+            }
+            Ok(inner.attestations.get(content_hash).cloned())
+        };
+        Self::confirm(result, fault)
+    }
+
+    // =========================================================================
+    // Publisher Bonds
+    // =========================================================================
+
+    async fn post_bond(&self, amount: u64) -> SettleResult<TransactionId> {
+        let fault = self.inject().await?;
+        let result = {
+            let mut inner = self.inner.write().unwrap();
+            if inner.should_fail {
+                return Err(SettleError::transaction_failed("mock: configured to fail"));
+            }
+            if inner.balance < amount {
+                return Err(SettleError::insufficient_balance(inner.balance, amount));
+            }
+            inner.balance -= amount;
+            let own_account = inner.own_account;
+            *inner.bonds.entry(own_account).or_insert(0) += amount;
+            Ok(Self::next_tx_id(&mut inner))
+        };
+        Self::confirm(result, fault)
+    }
+
+    async fn get_bond(&self, account: &AccountId) -> SettleResult<u64> {
+        let fault = self.inject().await?;
+        let result = {
+            let inner = self.inner.read().unwrap();
+            if inner.should_fail {
+                return Err(SettleError::transaction_failed("mock: configured to fail"));
+            }
+            Ok(inner.bonds.get(account).copied().unwrap_or(0))
+        };
+        Self::confirm(result, fault)
+    }
+
+    async fn slash_bond(
+        &self,
+        account: &AccountId,
+        amount: u64,
+        evidence: &Hash,
+    ) -> SettleResult<TransactionId> {
+        let fault = self.inject().await?;
+        let result = {
+            let mut inner = self.inner.write().unwrap();
+            if inner.should_fail {
+                return Err(SettleError::transaction_failed("mock: configured to fail"));
+            }
+            let bond = inner.bonds.entry(*account).or_insert(0);
+            let slashed = amount.min(*bond);
+            *bond -= slashed;
+            inner.slashes.push((*account, slashed, *evidence));
+            Ok(Self::next_tx_id(&mut inner))
+        };
+        Self::confirm(result, fault)
+    }
+
+    async fn submit_fraud_proof(
+        &self,
+        provider: &AccountId,
+        proof_hash: &Hash,
+    ) -> SettleResult<TransactionId> {
+        let fault = self.inject().await?;
+        let result = {
+            let mut inner = self.inner.write().unwrap();
+            if inner.should_fail {
+                return Err(SettleError::transaction_failed("mock: configured to fail"));
+            }
+            if inner.fraud_proofs.contains_key(proof_hash) {
+                return Err(SettleError::transaction_failed(
+                    "mock: fraud proof already recorded",
+                ));
+            }
+            inner.fraud_proofs.insert(*proof_hash, *provider);
+            Ok(Self::next_tx_id(&mut inner))
+        };
+        Self::confirm(result, fault)
     }
 
     // =========================================================================
@@ -235,20 +412,24 @@ impl Settlement for MockSettlement {
         peer: &PeerId,
         deposit: u64,
     ) -> SettleResult<TransactionId> {
-        let mut inner = self.inner.write().unwrap();
-        if inner.should_fail {
-            return Err(SettleError::transaction_failed("mock: configured to fail"));
-        }
-        if inner.balance < deposit {
-            return Err(SettleError::insufficient_balance(inner.balance, deposit));
-        }
-        inner.balance -= deposit;
-
-        inner
-            .channels
-            .insert(channel_id.to_string(), (*peer, deposit));
-
-        Ok(Self::next_tx_id(&mut inner))
+        let fault = self.inject().await?;
+        let result = {
+            let mut inner = self.inner.write().unwrap();
+            if inner.should_fail {
+                return Err(SettleError::transaction_failed("mock: configured to fail"));
+            }
+            if inner.balance < deposit {
+                return Err(SettleError::insufficient_balance(inner.balance, deposit));
+            }
+            inner.balance -= deposit;
+
+            inner
+                .channels
+                .insert(channel_id.to_string(), (*peer, deposit));
+
+            Ok(Self::next_tx_id(&mut inner))
+        };
+        Self::confirm(result, fault)
     }
 
     async fn close_channel(
@@ -257,59 +438,114 @@ impl Settlement for MockSettlement {
         _final_state: &ChannelBalances,
         _signatures: &[Signature],
     ) -> SettleResult<TransactionId> {
-        let mut inner = self.inner.write().unwrap();
-        if inner.should_fail {
-            return Err(SettleError::transaction_failed("mock: configured to fail"));
-        }
-        let key = channel_id.to_string();
-        if inner.channels.remove(&key).is_none() {
-            return Err(SettleError::channel_not_found(key));
-        }
-        Ok(Self::next_tx_id(&mut inner))
+        let fault = self.inject().await?;
+        let result = {
+            let mut inner = self.inner.write().unwrap();
+            if inner.should_fail {
+                return Err(SettleError::transaction_failed("mock: configured to fail"));
+            }
+            let key = channel_id.to_string();
+            if inner.channels.remove(&key).is_none() {
+                return Err(SettleError::channel_not_found(key));
+            }
+            Ok(Self::next_tx_id(&mut inner))
+        };
+        Self::confirm(result, fault)
     }
 
     async fn dispute_channel(
         &self,
         channel_id: &ChannelId,
-        _state: &ChannelUpdatePayload,
+        state: &ChannelUpdatePayload,
     ) -> SettleResult<TransactionId> {
-        let mut inner = self.inner.write().unwrap();
-        if inner.should_fail {
-            return Err(SettleError::transaction_failed("mock: configured to fail"));
-        }
-        let key = channel_id.to_string();
-        if !inner.channels.contains_key(&key) {
-            return Err(SettleError::channel_not_found(key));
-        }
-        Ok(Self::next_tx_id(&mut inner))
+        let fault = self.inject().await?;
+        let result = {
+            let mut inner = self.inner.write().unwrap();
+            if inner.should_fail {
+                return Err(SettleError::transaction_failed("mock: configured to fail"));
+            }
+            let key = channel_id.to_string();
+            if !inner.channels.contains_key(&key) {
+                return Err(SettleError::channel_not_found(key));
+            }
+            let dispute_start = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0);
+            inner.disputes.insert(
+                key,
+                OnChainDispute {
+                    channel_id: channel_id.clone(),
+                    dispute_start,
+                    resolvable_at: dispute_start + PendingDispute::DISPUTE_PERIOD_MS,
+                    nonce: state.nonce,
+                    balance1: state.balances.initiator,
+                    balance2: state.balances.responder,
+                },
+            );
+            Ok(Self::next_tx_id(&mut inner))
+        };
+        Self::confirm(result, fault)
     }
 
     async fn counter_dispute(
         &self,
         channel_id: &ChannelId,
-        _better_state: &ChannelUpdatePayload,
+        better_state: &ChannelUpdatePayload,
     ) -> SettleResult<TransactionId> {
-        let mut inner = self.inner.write().unwrap();
-        if inner.should_fail {
-            return Err(SettleError::transaction_failed("mock: configured to fail"));
-        }
-        let key = channel_id.to_string();
-        if !inner.channels.contains_key(&key) {
-            return Err(SettleError::channel_not_found(key));
-        }
-        Ok(Self::next_tx_id(&mut inner))
+        let fault = self.inject().await?;
+        let result = {
+            let mut inner = self.inner.write().unwrap();
+            if inner.should_fail {
+                return Err(SettleError::transaction_failed("mock: configured to fail"));
+            }
+            let key = channel_id.to_string();
+            if !inner.channels.contains_key(&key) {
+                return Err(SettleError::channel_not_found(key));
+            }
+            let Some(dispute) = inner.disputes.get_mut(&key) else {
+                return Err(SettleError::transaction_failed(
+                    "mock: channel not disputed",
+                ));
+            };
+            if better_state.nonce <= dispute.nonce {
+                return Err(SettleError::transaction_failed("mock: nonce not higher"));
+            }
+            dispute.nonce = better_state.nonce;
+            dispute.balance1 = better_state.balances.initiator;
+            dispute.balance2 = better_state.balances.responder;
+            Ok(Self::next_tx_id(&mut inner))
+        };
+        Self::confirm(result, fault)
     }
 
     async fn resolve_dispute(&self, channel_id: &ChannelId) -> SettleResult<TransactionId> {
-        let mut inner = self.inner.write().unwrap();
-        if inner.should_fail {
-            return Err(SettleError::transaction_failed("mock: configured to fail"));
-        }
-        let key = channel_id.to_string();
-        if inner.channels.remove(&key).is_none() {
-            return Err(SettleError::channel_not_found(key));
-        }
-        Ok(Self::next_tx_id(&mut inner))
+        let fault = self.inject().await?;
+        let result = {
+            let mut inner = self.inner.write().unwrap();
+            if inner.should_fail {
+                return Err(SettleError::transaction_failed("mock: configured to fail"));
+            }
+            let key = channel_id.to_string();
+            if inner.channels.remove(&key).is_none() {
+                return Err(SettleError::channel_not_found(key));
+            }
+            inner.disputes.remove(&key);
+            Ok(Self::next_tx_id(&mut inner))
+        };
+        Self::confirm(result, fault)
+    }
+
+    async fn get_dispute(&self, channel_id: &ChannelId) -> SettleResult<Option<OnChainDispute>> {
+        let fault = self.inject().await?;
+        let result = {
+            let inner = self.inner.read().unwrap();
+            if inner.should_fail {
+                return Err(SettleError::transaction_failed("mock: configured to fail"));
+            }
+            Ok(inner.disputes.get(&channel_id.to_string()).cloned())
+        };
+        Self::confirm(result, fault)
     }
 
     // =========================================================================
@@ -317,20 +553,28 @@ impl Settlement for MockSettlement {
     // =========================================================================
 
     async fn settle_batch(&self, batch: &SettlementBatch) -> SettleResult<TransactionId> {
-        let mut inner = self.inner.write().unwrap();
-        if inner.should_fail {
-            return Err(SettleError::transaction_failed("mock: configured to fail"));
-        }
-        inner.settled_batches.push(batch.clone());
-        Ok(Self::next_tx_id(&mut inner))
+        let fault = self.inject().await?;
+        let result = {
+            let mut inner = self.inner.write().unwrap();
+            if inner.should_fail {
+                return Err(SettleError::transaction_failed("mock: configured to fail"));
+            }
+            inner.settled_batches.push(batch.clone());
+            Ok(Self::next_tx_id(&mut inner))
+        };
+        Self::confirm(result, fault)
     }
 
     async fn verify_settlement(&self, _tx_id: &TransactionId) -> SettleResult<SettlementStatus> {
-        let inner = self.inner.read().unwrap();
-        if inner.should_fail {
-            return Err(SettleError::transaction_failed("mock: configured to fail"));
-        }
-        Ok(SettlementStatus::confirmed(1, 1234567890000))
+        let fault = self.inject().await?;
+        let result = {
+            let inner = self.inner.read().unwrap();
+            if inner.should_fail {
+                return Err(SettleError::transaction_failed("mock: configured to fail"));
+            }
+            Ok(SettlementStatus::confirmed(1, 1234567890000))
+        };
+        Self::confirm(result, fault)
     }
 
     // =========================================================================
@@ -395,6 +639,37 @@ mod tests {
         assert_eq!(att.unwrap().content_hash, hash);
     }
 
+    #[tokio::test]
+    async fn test_bond_lifecycle() {
+        let settle = MockSettlement::new().with_balance(1000);
+        let account = settle.get_own_account();
+        let evidence = content_hash(b"bad content");
+
+        assert!(settle.post_bond(2000).await.is_err());
+        settle.post_bond(400).await.unwrap();
+        settle.post_bond(100).await.unwrap();
+        assert_eq!(settle.get_bond(&account).await.unwrap(), 500);
+        assert_eq!(settle.current_balance(), 500);
+
+        // Slashing is capped at the bond
+        settle.slash_bond(&account, 800, &evidence).await.unwrap();
+        assert_eq!(settle.get_bond(&account).await.unwrap(), 0);
+        assert_eq!(settle.slashes(), vec![(account, 500, evidence)]);
+        assert_eq!(settle.get_bond(&AccountId::simple(1)).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fraud_proof_recorded_once() {
+        let settle = MockSettlement::new();
+        let provider = AccountId::simple(7);
+        let proof = content_hash(b"proof");
+
+        assert_eq!(settle.fraud_proof(&proof), None);
+        settle.submit_fraud_proof(&provider, &proof).await.unwrap();
+        assert_eq!(settle.fraud_proof(&proof), Some(provider));
+        assert!(settle.submit_fraud_proof(&provider, &proof).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_channel_lifecycle() {
         let settle = MockSettlement::new().with_balance(10000);
@@ -445,6 +720,36 @@ mod tests {
         assert!(status.is_confirmed());
     }
 
+    #[tokio::test]
+    async fn test_fault_injection_transient_failure() {
+        let faults = FaultInjector::new(5);
+        let settle = MockSettlement::new().with_fault_injector(faults.clone());
+        faults.fail_next_settlements(2);
+
+        for _ in 0..2 {
+            let err = settle.deposit(1000).await.unwrap_err();
+            assert!(err.is_retryable());
+        }
+        // Failed attempts take no effect; the retry succeeds once
+        settle.deposit(1000).await.unwrap();
+        assert_eq!(settle.deposits(), vec![1000]);
+        assert_eq!(faults.stats().settlement_failures, 2);
+    }
+
+    #[tokio::test]
+    async fn test_fault_injection_lost_ack() {
+        let faults = FaultInjector::new(5).with_lost_ack_rate(1.0);
+        let settle = MockSettlement::new().with_fault_injector(faults.clone());
+
+        let err = settle.deposit(1000).await.unwrap_err();
+        assert!(matches!(err, SettleError::Timeout(_)));
+        // The deposit went through even though the caller saw an error
+        assert_eq!(settle.deposits(), vec![1000]);
+
+        faults.set_enabled(false);
+        assert_eq!(settle.get_balance().await.unwrap(), 1000);
+    }
+
     #[test]
     fn test_peer_account_mapping() {
         let settle = MockSettlement::new();
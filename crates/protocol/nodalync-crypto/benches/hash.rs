@@ -0,0 +1,21 @@
+//! Content hashing benchmarks.
+//!
+//! Run with `cargo bench -p nodalync-crypto`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use nodalync_crypto::content_hash;
+
+fn bench_content_hash(c: &mut Criterion) {
+    let mut group = c.benchmark_group("content_hash");
+    for size in [1 << 10, 64 << 10, 1 << 20, 16 << 20] {
+        let data = vec![0xA5u8; size];
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &data, |b, data| {
+            b.iter(|| content_hash(black_box(data)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_content_hash);
+criterion_main!(benches);
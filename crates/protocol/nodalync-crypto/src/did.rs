@@ -0,0 +1,182 @@
+//! `did:key` identifiers for Nodalync identities.
+//!
+//! A Nodalync identity is an Ed25519 keypair, so it maps directly onto the
+//! `did:key` method used by verifiable-credential tooling:
+//! ```text
+//! DID = "did:key:" + multibase(base58btc, 0xed 0x01 || public_key)
+//! ```
+//!
+//! The multibase prefix for base58btc is `z`, so Ed25519 DIDs start with
+//! `did:key:z6Mk`. Unlike the PeerId, which is a truncated hash, the DID
+//! carries the whole public key and resolves without a lookup.
+
+use ed25519_dalek::VerifyingKey;
+
+use crate::error::CryptoError;
+use crate::{peer_id_from_public_key, PeerId, PublicKey};
+
+/// Prefix of every `did:key` identifier
+pub const DID_KEY_PREFIX: &str = "did:key:";
+
+/// Multibase prefix for base58btc
+const MULTIBASE_BASE58BTC: char = 'z';
+
+/// Multicodec prefix for an Ed25519 public key (varint 0xed)
+const ED25519_PUB_MULTICODEC: [u8; 2] = [0xed, 0x01];
+
+/// Encode a public key as a multibase multicodec string (`z6Mk...`).
+///
+/// This is the `publicKeyMultibase` value of the key's verification
+/// method, and the method-specific part of its DID.
+pub fn public_key_to_multibase(public_key: &PublicKey) -> String {
+    let mut bytes = Vec::with_capacity(ED25519_PUB_MULTICODEC.len() + public_key.0.len());
+    bytes.extend_from_slice(&ED25519_PUB_MULTICODEC);
+    bytes.extend_from_slice(&public_key.0);
+    format!(
+        "{}{}",
+        MULTIBASE_BASE58BTC,
+        bs58::encode(bytes).into_string()
+    )
+}
+
+/// Decode a multibase multicodec Ed25519 public key.
+///
+/// # Errors
+/// - `InvalidDid` if the multibase or multicodec prefix is not base58btc
+///   Ed25519
+/// - `InvalidBase58` if the base58 decoding fails
+/// - `InvalidKeyLength` if the key isn't 32 bytes
+/// - `InvalidPublicKey` if the bytes are not a valid Ed25519 key
+pub fn public_key_from_multibase(s: &str) -> Result<PublicKey, CryptoError> {
+    let encoded = s.strip_prefix(MULTIBASE_BASE58BTC).ok_or_else(|| {
+        CryptoError::InvalidDid(format!(
+            "expected base58btc multibase ('{}')",
+            MULTIBASE_BASE58BTC
+        ))
+    })?;
+
+    let decoded = bs58::decode(encoded)
+        .into_vec()
+        .map_err(|e| CryptoError::InvalidBase58(e.to_string()))?;
+
+    let key = decoded
+        .strip_prefix(&ED25519_PUB_MULTICODEC)
+        .ok_or_else(|| CryptoError::InvalidDid("not an Ed25519 public key".to_string()))?;
+
+    let bytes: [u8; 32] = key.try_into().map_err(|_| CryptoError::InvalidKeyLength {
+        expected: 32,
+        actual: key.len(),
+    })?;
+
+    VerifyingKey::from_bytes(&bytes).map_err(|_| CryptoError::InvalidPublicKey)?;
+    Ok(PublicKey(bytes))
+}
+
+/// Get the `did:key` identifier for a public key.
+///
+/// # Example
+/// ```
+/// use nodalync_crypto::{did_from_public_key, generate_identity};
+///
+/// let (_, public_key) = generate_identity();
+/// assert!(did_from_public_key(&public_key).starts_with("did:key:z6Mk"));
+/// ```
+pub fn did_from_public_key(public_key: &PublicKey) -> String {
+    format!("{}{}", DID_KEY_PREFIX, public_key_to_multibase(public_key))
+}
+
+/// Resolve a `did:key` identifier to its public key.
+///
+/// Accepts a DID URL with a fragment (`did:key:z6Mk...#z6Mk...`) and
+/// resolves its DID.
+///
+/// # Errors
+/// - `InvalidDid` if the string is not a `did:key` identifier
+/// - Any error from [`public_key_from_multibase`]
+///
+/// # Example
+/// ```
+/// use nodalync_crypto::{did_from_public_key, generate_identity, public_key_from_did};
+///
+/// let (_, public_key) = generate_identity();
+/// let did = did_from_public_key(&public_key);
+/// assert_eq!(public_key_from_did(&did).unwrap(), public_key);
+/// ```
+pub fn public_key_from_did(did: &str) -> Result<PublicKey, CryptoError> {
+    let did = did.split('#').next().unwrap_or(did);
+    let method_specific = did
+        .strip_prefix(DID_KEY_PREFIX)
+        .ok_or_else(|| CryptoError::InvalidDid(format!("expected '{}' prefix", DID_KEY_PREFIX)))?;
+    public_key_from_multibase(method_specific)
+}
+
+/// Resolve a `did:key` identifier to the PeerId of its key.
+pub fn peer_id_from_did(did: &str) -> Result<PeerId, CryptoError> {
+    public_key_from_did(did).map(|public_key| peer_id_from_public_key(&public_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_identity;
+
+    #[test]
+    fn test_did_roundtrip() {
+        let (_, public_key) = generate_identity();
+        let did = did_from_public_key(&public_key);
+        assert!(did.starts_with("did:key:z6Mk"));
+        assert_eq!(public_key_from_did(&did).unwrap(), public_key);
+        assert_eq!(
+            peer_id_from_did(&did).unwrap(),
+            peer_id_from_public_key(&public_key)
+        );
+
+        // DID URLs resolve to their DID's key
+        let url = format!("{}#{}", did, public_key_to_multibase(&public_key));
+        assert_eq!(public_key_from_did(&url).unwrap(), public_key);
+    }
+
+    #[test]
+    fn test_did_key_test_vector() {
+        // Ed25519 example from the did:key method specification
+        let did = "did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK";
+        let public_key = public_key_from_did(did).unwrap();
+        assert_eq!(did_from_public_key(&public_key), did);
+    }
+
+    #[test]
+    fn test_invalid_dids() {
+        let (_, public_key) = generate_identity();
+        let multibase = public_key_to_multibase(&public_key);
+
+        // Other DID methods
+        assert!(matches!(
+            public_key_from_did(&format!("did:web:{}", multibase)),
+            Err(CryptoError::InvalidDid(_))
+        ));
+
+        // Other multibase encodings
+        assert!(matches!(
+            public_key_from_did(&format!("did:key:f{}", &multibase[1..])),
+            Err(CryptoError::InvalidDid(_))
+        ));
+
+        // Other key types (secp256k1 multicodec 0xe7 0x01)
+        let mut bytes = vec![0xe7, 0x01];
+        bytes.extend_from_slice(&public_key.0);
+        let secp = format!("did:key:z{}", bs58::encode(bytes).into_string());
+        assert!(matches!(
+            public_key_from_did(&secp),
+            Err(CryptoError::InvalidDid(_))
+        ));
+
+        // Truncated keys
+        let mut bytes = ED25519_PUB_MULTICODEC.to_vec();
+        bytes.extend_from_slice(&public_key.0[..31]);
+        let short = format!("did:key:z{}", bs58::encode(bytes).into_string());
+        assert!(matches!(
+            public_key_from_did(&short),
+            Err(CryptoError::InvalidKeyLength { .. })
+        ));
+    }
+}
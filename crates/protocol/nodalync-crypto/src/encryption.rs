@@ -0,0 +1,295 @@
+//! Content encryption for restricted delivery.
+//!
+//! Restricted content is encrypted under a per-content symmetric key
+//! (AES-256-GCM), and that key is wrapped to each authorized peer. Wrapping
+//! uses an ephemeral X25519 exchange with the peer's X25519 key, which is
+//! the Montgomery form of its Ed25519 identity key, so no separate key
+//! distribution is needed:
+//!
+//! ```text
+//! shared  = X25519(ephemeral_secret, X25519(recipient_key))
+//! kek     = H("nodalync-key-wrap" || shared || ephemeral_public || X25519(recipient_key))
+//! wrapped = AES-256-GCM(kek, content_key)
+//! ```
+//!
+//! [`seal`] applies the same scheme to arbitrary data for one recipient,
+//! e.g. one layer of a relayed query.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use ed25519_dalek::VerifyingKey;
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use x25519_dalek::{x25519, X25519_BASEPOINT_BYTES};
+
+use crate::error::CryptoError;
+use crate::{PrivateKey, PublicKey};
+
+/// Domain separator for key-encryption keys.
+const DOMAIN_KEY_WRAP: &[u8] = b"nodalync-key-wrap";
+
+/// Nonce length for AES-GCM.
+const NONCE_LEN: usize = 12;
+
+/// A symmetric key for one piece of content (32 bytes).
+#[derive(Clone, PartialEq, Eq)]
+pub struct ContentKey([u8; 32]);
+
+impl ContentKey {
+    /// Generate a random content key.
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        Self(bytes)
+    }
+
+    /// Create a ContentKey from raw bytes.
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Get the raw bytes of the key.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for ContentKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ContentKey([REDACTED])")
+    }
+}
+
+/// A content key wrapped to one recipient.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WrappedKey {
+    /// The sender's ephemeral X25519 public key.
+    pub ephemeral_key: [u8; 32],
+    /// The content key encrypted under the key-encryption key.
+    pub ciphertext: Vec<u8>,
+}
+
+/// Data sealed to one recipient with [`seal`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedData {
+    /// A fresh content key, wrapped to the recipient.
+    pub key: WrappedKey,
+    /// The data encrypted under that key (see [`encrypt_content`]).
+    pub ciphertext: Vec<u8>,
+}
+
+/// Encrypt content under a content key.
+///
+/// Returns `nonce || ciphertext`, with a fresh random nonce.
+pub fn encrypt_content(key: &ContentKey, plaintext: &[u8]) -> Vec<u8> {
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce_bytes);
+
+    let ciphertext = Aes256Gcm::new(key.as_bytes().into())
+        .encrypt(Nonce::from_slice(&nonce_bytes), plaintext)
+        .expect("AES-GCM encryption of an in-memory buffer cannot fail");
+
+    let mut output = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    output.extend_from_slice(&nonce_bytes);
+    output.extend_from_slice(&ciphertext);
+    output
+}
+
+/// Decrypt content produced by [`encrypt_content`].
+pub fn decrypt_content(key: &ContentKey, data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if data.len() < NONCE_LEN {
+        return Err(CryptoError::DecryptionFailed);
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    Aes256Gcm::new(key.as_bytes().into())
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| CryptoError::DecryptionFailed)
+}
+
+/// Wrap a content key to the holder of `recipient`.
+///
+/// Fails if `recipient` is not a valid Ed25519 public key.
+pub fn wrap_content_key(
+    key: &ContentKey,
+    recipient: &PublicKey,
+) -> Result<WrappedKey, CryptoError> {
+    let recipient_x25519 = x25519_public_key(recipient)?;
+
+    let mut ephemeral_secret = [0u8; 32];
+    OsRng.fill_bytes(&mut ephemeral_secret);
+    let ephemeral_key = x25519(ephemeral_secret, X25519_BASEPOINT_BYTES);
+
+    let shared = x25519(ephemeral_secret, recipient_x25519);
+    if shared == [0u8; 32] {
+        return Err(CryptoError::InvalidPublicKey);
+    }
+
+    let kek = key_encryption_key(&shared, &ephemeral_key, &recipient_x25519);
+    // Each key-encryption key is used once, so a fixed nonce is safe
+    let ciphertext = Aes256Gcm::new(&kek.into())
+        .encrypt(
+            Nonce::from_slice(&[0u8; NONCE_LEN]),
+            key.as_bytes().as_ref(),
+        )
+        .expect("AES-GCM encryption of an in-memory buffer cannot fail");
+
+    Ok(WrappedKey {
+        ephemeral_key,
+        ciphertext,
+    })
+}
+
+/// Unwrap a content key wrapped to our identity.
+pub fn unwrap_content_key(
+    wrapped: &WrappedKey,
+    private_key: &PrivateKey,
+) -> Result<ContentKey, CryptoError> {
+    let signing_key = private_key.to_signing_key();
+    let own_x25519 = signing_key.verifying_key().to_montgomery().to_bytes();
+
+    let shared = x25519(signing_key.to_scalar_bytes(), wrapped.ephemeral_key);
+    if shared == [0u8; 32] {
+        return Err(CryptoError::DecryptionFailed);
+    }
+
+    let kek = key_encryption_key(&shared, &wrapped.ephemeral_key, &own_x25519);
+    let plaintext = Aes256Gcm::new(&kek.into())
+        .decrypt(
+            Nonce::from_slice(&[0u8; NONCE_LEN]),
+            wrapped.ciphertext.as_ref(),
+        )
+        .map_err(|_| CryptoError::DecryptionFailed)?;
+
+    let bytes: [u8; 32] = plaintext
+        .try_into()
+        .map_err(|_| CryptoError::DecryptionFailed)?;
+    Ok(ContentKey(bytes))
+}
+
+/// Seal data to the holder of `recipient`, under a fresh content key.
+pub fn seal(recipient: &PublicKey, plaintext: &[u8]) -> Result<SealedData, CryptoError> {
+    let key = ContentKey::generate();
+    Ok(SealedData {
+        key: wrap_content_key(&key, recipient)?,
+        ciphertext: encrypt_content(&key, plaintext),
+    })
+}
+
+/// Open data sealed to our identity with [`seal`].
+pub fn open_sealed(sealed: &SealedData, private_key: &PrivateKey) -> Result<Vec<u8>, CryptoError> {
+    let key = unwrap_content_key(&sealed.key, private_key)?;
+    decrypt_content(&key, &sealed.ciphertext)
+}
+
+/// Convert an Ed25519 public key to its X25519 (Montgomery) form.
+fn x25519_public_key(key: &PublicKey) -> Result<[u8; 32], CryptoError> {
+    let verifying_key =
+        VerifyingKey::from_bytes(&key.0).map_err(|_| CryptoError::InvalidPublicKey)?;
+    Ok(verifying_key.to_montgomery().to_bytes())
+}
+
+/// Derive the key-encryption key for one wrap.
+fn key_encryption_key(shared: &[u8; 32], ephemeral: &[u8; 32], recipient: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(DOMAIN_KEY_WRAP);
+    hasher.update(shared);
+    hasher.update(ephemeral);
+    hasher.update(recipient);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_identity;
+
+    #[test]
+    fn test_content_roundtrip() {
+        let key = ContentKey::generate();
+        let plaintext = b"Restricted research notes";
+
+        let encrypted = encrypt_content(&key, plaintext);
+        assert_ne!(&encrypted[NONCE_LEN..], plaintext.as_slice());
+        assert_eq!(decrypt_content(&key, &encrypted).unwrap(), plaintext);
+
+        // Fresh nonce every time
+        assert_ne!(encrypt_content(&key, plaintext), encrypted);
+
+        // Wrong key, tampering and truncation fail
+        let other = ContentKey::generate();
+        assert_eq!(
+            decrypt_content(&other, &encrypted),
+            Err(CryptoError::DecryptionFailed)
+        );
+        let mut tampered = encrypted.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt_content(&key, &tampered).is_err());
+        assert!(decrypt_content(&key, &encrypted[..4]).is_err());
+    }
+
+    #[test]
+    fn test_wrap_unwrap() {
+        let key = ContentKey::generate();
+        let (private_key, public_key) = generate_identity();
+
+        let wrapped = wrap_content_key(&key, &public_key).unwrap();
+        assert_eq!(unwrap_content_key(&wrapped, &private_key).unwrap(), key);
+
+        // Each wrap uses a fresh ephemeral key
+        let again = wrap_content_key(&key, &public_key).unwrap();
+        assert_ne!(again.ephemeral_key, wrapped.ephemeral_key);
+    }
+
+    #[test]
+    fn test_unwrap_wrong_recipient() {
+        let key = ContentKey::generate();
+        let (_, public_key) = generate_identity();
+        let (other_key, _) = generate_identity();
+
+        let wrapped = wrap_content_key(&key, &public_key).unwrap();
+        assert_eq!(
+            unwrap_content_key(&wrapped, &other_key),
+            Err(CryptoError::DecryptionFailed)
+        );
+
+        let (private_key, public_key) = generate_identity();
+        let wrapped = wrap_content_key(&key, &public_key).unwrap();
+        let mut tampered = wrapped.clone();
+        tampered.ciphertext[0] ^= 1;
+        assert!(unwrap_content_key(&tampered, &private_key).is_err());
+        let mut tampered = wrapped;
+        tampered.ephemeral_key[0] ^= 1;
+        assert!(unwrap_content_key(&tampered, &private_key).is_err());
+    }
+
+    #[test]
+    fn test_seal_open() {
+        let (private_key, public_key) = generate_identity();
+        let (other_key, _) = generate_identity();
+
+        let sealed = seal(&public_key, b"route layer").unwrap();
+        assert_eq!(open_sealed(&sealed, &private_key).unwrap(), b"route layer");
+        assert_eq!(
+            open_sealed(&sealed, &other_key),
+            Err(CryptoError::DecryptionFailed)
+        );
+
+        let mut tampered = sealed;
+        tampered.ciphertext[NONCE_LEN] ^= 1;
+        assert!(open_sealed(&tampered, &private_key).is_err());
+    }
+
+    #[test]
+    fn test_wrap_low_order_key() {
+        // The Ed25519 identity point has no usable X25519 form
+        let mut bytes = [0u8; 32];
+        bytes[0] = 1;
+        assert_eq!(
+            wrap_content_key(&ContentKey::generate(), &PublicKey(bytes)),
+            Err(CryptoError::InvalidPublicKey)
+        );
+    }
+}
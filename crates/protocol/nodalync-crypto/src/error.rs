@@ -24,4 +24,16 @@ pub enum CryptoError {
     /// Signature verification failed
     #[error("Signature verification failed")]
     SignatureVerificationFailed,
+
+    /// Public key is not usable for key agreement
+    #[error("Invalid public key")]
+    InvalidPublicKey,
+
+    /// Decryption failed (wrong key or tampered data)
+    #[error("Decryption failed")]
+    DecryptionFailed,
+
+    /// Not a supported `did:key` identifier
+    #[error("Invalid DID: {0}")]
+    InvalidDid(String),
 }
@@ -4,6 +4,16 @@
 //! ```text
 //! ContentHash(content) = H(0x00 || len(content) as u64be || content)
 //! ```
+//!
+//! Chunks of large content are hashed on their own, bound to their position:
+//! ```text
+//! ChunkHash(index, chunk) = H(0x03 || index as u32be || len(chunk) as u64be || chunk)
+//! ```
+//!
+//! Sections sold on their own are hashed with their byte offset:
+//! ```text
+//! SectionHash(offset, slice) = H(0x04 || offset as u64be || len(slice) as u64be || slice)
+//! ```
 
 use sha2::{Digest, Sha256};
 
@@ -12,6 +22,12 @@ use crate::Hash;
 /// Domain separator for content hashing (Spec Appendix A.2)
 const DOMAIN_CONTENT: u8 = 0x00;
 
+/// Domain separator for chunk hashing
+const DOMAIN_CHUNK: u8 = 0x03;
+
+/// Domain separator for section hashing
+const DOMAIN_SECTION: u8 = 0x04;
+
 /// Compute the content hash of the given bytes.
 ///
 /// Uses SHA-256 with domain separation to prevent hash collisions across different uses.
@@ -65,6 +81,46 @@ pub fn verify_content(content: &[u8], expected: &Hash) -> bool {
     computed.0 == expected.0
 }
 
+/// Compute the hash of one chunk of a larger piece of content.
+///
+/// The chunk's index is hashed in, so a chunk served at the wrong position
+/// doesn't verify.
+///
+/// # Algorithm
+/// ```text
+/// H(0x03 || index as uint32_be || len(chunk) as uint64_be || chunk)
+/// ```
+pub fn chunk_hash(index: u32, chunk: &[u8]) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([DOMAIN_CHUNK]);
+    hasher.update(index.to_be_bytes());
+    hasher.update((chunk.len() as u64).to_be_bytes());
+    hasher.update(chunk);
+
+    let result: [u8; 32] = hasher.finalize().into();
+    Hash(result)
+}
+
+/// Compute the hash of a section of a piece of content.
+///
+/// The section's byte offset is hashed in, so a slice taken from elsewhere
+/// in the content doesn't verify.
+///
+/// # Algorithm
+/// ```text
+/// H(0x04 || offset as uint64_be || len(slice) as uint64_be || slice)
+/// ```
+pub fn section_hash(offset: u64, slice: &[u8]) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([DOMAIN_SECTION]);
+    hasher.update(offset.to_be_bytes());
+    hasher.update((slice.len() as u64).to_be_bytes());
+    hasher.update(slice);
+
+    let result: [u8; 32] = hasher.finalize().into();
+    Hash(result)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -89,4 +145,22 @@ mod tests {
         assert!(verify_content(content, &hash));
         assert!(!verify_content(b"tampered", &hash));
     }
+
+    #[test]
+    fn test_chunk_hash() {
+        let chunk = b"chunk bytes";
+        assert_eq!(chunk_hash(0, chunk), chunk_hash(0, chunk));
+        // Bound to the position, and separate from content hashes
+        assert_ne!(chunk_hash(0, chunk), chunk_hash(1, chunk));
+        assert_ne!(chunk_hash(0, chunk), content_hash(chunk));
+    }
+
+    #[test]
+    fn test_section_hash() {
+        let slice = b"section bytes";
+        assert_eq!(section_hash(0, slice), section_hash(0, slice));
+        // Bound to the offset, and separate from chunk hashes
+        assert_ne!(section_hash(0, slice), section_hash(10, slice));
+        assert_ne!(section_hash(0, slice), chunk_hash(0, slice));
+    }
 }
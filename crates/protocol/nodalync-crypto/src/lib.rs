@@ -6,6 +6,16 @@
 //! - **Identity** (§3.2): Ed25519 keypair generation and PeerId derivation
 //! - **Signatures** (§3.3): Message signing and verification
 //! - **Content Addressing** (§3.4): Content verification by hash
+//! - **Content Encryption** (§3.5): Per-content keys wrapped to X25519 recipients
+//! - **DIDs**: `did:key` identifiers for identities, for verifiable-credential tooling
+//! - **Time**: The current protocol timestamp, from the platform clock
+//!
+//! # WebAssembly
+//!
+//! The crate builds for `wasm32-unknown-unknown`. There, key generation and
+//! encryption draw randomness from the browser's `crypto.getRandomValues`
+//! and [`current_timestamp`] reads `Date.now()`, so web clients can sign
+//! and verify without a native runtime.
 //!
 //! # Example
 //!
@@ -32,18 +42,30 @@
 //! assert!(verify(&public_key, message, &signature));
 //! ```
 
+mod did;
+mod encryption;
 mod error;
 mod hash;
 mod identity;
 mod serde_impl;
 mod signature;
+mod time;
 
+pub use did::{
+    did_from_public_key, peer_id_from_did, public_key_from_did, public_key_from_multibase,
+    public_key_to_multibase, DID_KEY_PREFIX,
+};
+pub use encryption::{
+    decrypt_content, encrypt_content, open_sealed, seal, unwrap_content_key, wrap_content_key,
+    ContentKey, SealedData, WrappedKey,
+};
 pub use error::CryptoError;
-pub use hash::{content_hash, verify_content};
+pub use hash::{chunk_hash, content_hash, section_hash, verify_content};
 pub use identity::{
     generate_identity, peer_id_from_public_key, peer_id_from_string, peer_id_to_string,
 };
 pub use signature::{sign, verify, SignedMessage};
+pub use time::current_timestamp;
 
 use ed25519_dalek::SigningKey;
 
@@ -89,6 +111,11 @@ impl PrivateKey {
         &self.0
     }
 
+    /// Derive the public key for this private key.
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(self.to_signing_key().verifying_key().to_bytes())
+    }
+
     /// Create from an ed25519-dalek SigningKey.
     pub(crate) fn from_signing_key(key: &SigningKey) -> Self {
         Self(key.to_bytes())
@@ -248,6 +275,12 @@ mod tests {
         assert_eq!(public_key.0, pk_copy.0);
     }
 
+    #[test]
+    fn test_private_key_public_key() {
+        let (private_key, public_key) = generate_identity();
+        assert_eq!(private_key.public_key(), public_key);
+    }
+
     #[test]
     fn test_private_key_debug_redacted() {
         let (private_key, _) = generate_identity();
@@ -0,0 +1,33 @@
+//! Current time as a protocol timestamp.
+//!
+//! `std::time::SystemTime` panics on `wasm32-unknown-unknown`, so in the
+//! browser the clock is read from JavaScript instead.
+
+use crate::Timestamp;
+
+/// Current time in milliseconds since the Unix epoch.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub fn current_timestamp() -> Timestamp {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as Timestamp)
+        .unwrap_or(0)
+}
+
+/// Current time in milliseconds since the Unix epoch.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub fn current_timestamp() -> Timestamp {
+    js_sys::Date::now() as Timestamp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_timestamp_is_recent() {
+        // 2024-01-01T00:00:00Z
+        assert!(current_timestamp() > 1_704_067_200_000);
+        assert!(current_timestamp() <= current_timestamp());
+    }
+}
@@ -0,0 +1,54 @@
+//! Revenue distribution and merkle benchmarks.
+//!
+//! Run with `cargo bench -p nodalync-econ`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use nodalync_crypto::{content_hash, Hash, PeerId};
+use nodalync_econ::{compute_merkle_root, distribute_revenue};
+use nodalync_types::{ProvenanceEntry, SettlementEntry, Visibility};
+
+fn peer(i: u32) -> PeerId {
+    let mut id = [0u8; 20];
+    id[..4].copy_from_slice(&i.to_be_bytes());
+    PeerId(id)
+}
+
+fn hash(i: u32) -> Hash {
+    content_hash(&i.to_be_bytes())
+}
+
+fn bench_distribute_revenue(c: &mut Criterion) {
+    let owner = peer(u32::MAX);
+    let mut group = c.benchmark_group("distribute_revenue");
+    for contributors in [10u32, 100, 1_000, 10_000] {
+        let provenance: Vec<ProvenanceEntry> = (0..contributors)
+            .map(|i| ProvenanceEntry::with_weight(hash(i), peer(i), Visibility::Shared, 1 + i % 5))
+            .collect();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(contributors),
+            &provenance,
+            |b, provenance| {
+                b.iter(|| distribute_revenue(black_box(1_000_000_000), &owner, provenance))
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_merkle_root(c: &mut Criterion) {
+    let mut group = c.benchmark_group("merkle_root");
+    for entries in [16u32, 256, 4_096] {
+        let entries: Vec<SettlementEntry> = (0..entries)
+            .map(|i| SettlementEntry::new(peer(i), 1_000 + i as u64, vec![hash(i)], vec![hash(i)]))
+            .collect();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(entries.len()),
+            &entries,
+            |b, entries| b.iter(|| compute_merkle_root(black_box(entries))),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_distribute_revenue, bench_merkle_root);
+criterion_main!(benches);
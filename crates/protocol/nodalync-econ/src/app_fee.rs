@@ -0,0 +1,189 @@
+//! App-level platform fees.
+//!
+//! Applications embedding a node may take a platform fee on the queries
+//! they serve. The fee is a percentage of each payment, taken before the
+//! protocol distribution, and is paid to the app's recipient through the
+//! same settlement batch as everyone else, so it is visible on-chain and
+//! in payment receipts.
+
+use std::collections::HashMap;
+
+use nodalync_crypto::{Hash, PeerId};
+use nodalync_types::{
+    Amount, Distribution, ProvenanceEntry, BASIS_POINTS_DENOMINATOR, MAX_APP_FEE_BASIS_POINTS,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::distribution::distribute_revenue;
+use crate::error::{EconError, EconResult};
+
+/// A platform fee charged by the application serving content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppFee {
+    /// Peer receiving the fee.
+    pub recipient: PeerId,
+    /// Fee in basis points of each payment (100 = 1%).
+    pub basis_points: u32,
+}
+
+impl AppFee {
+    /// Create an app fee.
+    ///
+    /// Fails if the fee is zero or above [`MAX_APP_FEE_BASIS_POINTS`].
+    pub fn new(recipient: PeerId, basis_points: u32) -> EconResult<Self> {
+        if basis_points == 0 || basis_points > MAX_APP_FEE_BASIS_POINTS {
+            return Err(EconError::InvalidAppFee {
+                basis_points,
+                max: MAX_APP_FEE_BASIS_POINTS,
+            });
+        }
+        Ok(Self {
+            recipient,
+            basis_points,
+        })
+    }
+
+    /// Create an app fee from a percentage (e.g. `2.5` for 2.5%).
+    ///
+    /// The percentage is rounded to the nearest basis point.
+    pub fn from_percent(recipient: PeerId, percent: f64) -> EconResult<Self> {
+        let basis_points = (percent * 100.0).round();
+        if !basis_points.is_finite() || basis_points < 0.0 || basis_points > u32::MAX as f64 {
+            return Err(EconError::InvalidAppFee {
+                basis_points: u32::MAX,
+                max: MAX_APP_FEE_BASIS_POINTS,
+            });
+        }
+        Self::new(recipient, basis_points as u32)
+    }
+
+    /// The fee as a percentage.
+    pub fn percent(&self) -> f64 {
+        self.basis_points as f64 / 100.0
+    }
+
+    /// Calculate the fee on a payment amount (rounded down).
+    pub fn amount(&self, payment_amount: Amount) -> Amount {
+        (payment_amount as u128 * self.basis_points as u128 / BASIS_POINTS_DENOMINATOR as u128)
+            as Amount
+    }
+}
+
+/// Distribute payment revenue, taking an optional app fee first.
+///
+/// The app fee is taken off the top of the payment; the rest is split by
+/// [`distribute_revenue`]. Without a fee this is identical to
+/// `distribute_revenue`. Distributions are aggregated by recipient (the app
+/// recipient may also be the owner or a root contributor) and sorted.
+pub fn distribute_revenue_with_app_fee(
+    payment_amount: Amount,
+    owner: &PeerId,
+    provenance: &[ProvenanceEntry],
+    app_fee: Option<&AppFee>,
+) -> Vec<Distribution> {
+    let Some(app_fee) = app_fee else {
+        return distribute_revenue(payment_amount, owner, provenance);
+    };
+
+    let fee = app_fee.amount(payment_amount);
+    let mut amounts: HashMap<PeerId, Amount> = HashMap::new();
+    for dist in distribute_revenue(payment_amount - fee, owner, provenance) {
+        *amounts.entry(dist.recipient).or_default() += dist.amount;
+    }
+    *amounts.entry(app_fee.recipient).or_default() += fee;
+
+    let mut distributions: Vec<Distribution> = amounts
+        .into_iter()
+        .filter(|(_, amount)| *amount > 0)
+        .map(|(recipient, amount)| Distribution::new(recipient, amount, Hash([0u8; 32])))
+        .collect();
+    distributions.sort_by(|a, b| a.recipient.0.cmp(&b.recipient.0));
+    distributions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_types::Visibility;
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    fn amount_for(distributions: &[Distribution], peer: &PeerId) -> Amount {
+        distributions
+            .iter()
+            .find(|d| d.recipient == *peer)
+            .map(|d| d.amount)
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn test_app_fee_bounds() {
+        let app = test_peer_id();
+        assert!(AppFee::new(app, 0).is_err());
+        assert!(AppFee::new(app, MAX_APP_FEE_BASIS_POINTS + 1).is_err());
+        assert_eq!(AppFee::new(app, 250).unwrap().percent(), 2.5);
+
+        assert_eq!(AppFee::from_percent(app, 2.5).unwrap().basis_points, 250);
+        assert!(AppFee::from_percent(app, -1.0).is_err());
+        assert!(AppFee::from_percent(app, f64::NAN).is_err());
+        assert!(AppFee::from_percent(app, 50.0).is_err());
+    }
+
+    #[test]
+    fn test_app_fee_taken_before_distribution() {
+        let owner = test_peer_id();
+        let root = test_peer_id();
+        let app = test_peer_id();
+        let entry = ProvenanceEntry::with_weight(content_hash(b"src"), root, Visibility::Shared, 1);
+        let fee = AppFee::new(app, 1_000).unwrap();
+
+        let distributions = distribute_revenue_with_app_fee(1_000, &owner, &[entry], Some(&fee));
+
+        // 100 to the app; 900 split 5/95 between owner and root
+        assert_eq!(amount_for(&distributions, &app), 100);
+        assert_eq!(amount_for(&distributions, &owner), 45);
+        assert_eq!(amount_for(&distributions, &root), 855);
+        assert_eq!(
+            distributions.iter().map(|d| d.amount).sum::<Amount>(),
+            1_000
+        );
+    }
+
+    #[test]
+    fn test_app_fee_aggregates_with_owner_and_rounds_down() {
+        let owner = test_peer_id();
+        let entry =
+            ProvenanceEntry::with_weight(content_hash(b"src"), owner, Visibility::Shared, 1);
+        let fee = AppFee::new(owner, 150).unwrap();
+
+        let distributions = distribute_revenue_with_app_fee(99, &owner, &[entry], Some(&fee));
+        assert_eq!(distributions.len(), 1);
+        assert_eq!(distributions[0].amount, 99);
+
+        // 1.5% of 10 rounds down to nothing
+        assert_eq!(fee.amount(10), 0);
+        // No overflow on large payments
+        assert_eq!(fee.amount(u64::MAX), 276_701_161_105_643_274);
+    }
+
+    #[test]
+    fn test_no_app_fee_matches_protocol_distribution() {
+        let owner = test_peer_id();
+        let root = test_peer_id();
+        let provenance = vec![ProvenanceEntry::with_weight(
+            content_hash(b"src"),
+            root,
+            Visibility::Shared,
+            3,
+        )];
+
+        assert_eq!(
+            distribute_revenue_with_app_fee(1_234, &owner, &provenance, None),
+            distribute_revenue(1_234, &owner, &provenance)
+        );
+    }
+}
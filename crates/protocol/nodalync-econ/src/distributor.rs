@@ -5,8 +5,9 @@
 
 use nodalync_types::{Distribution, Payment, ProvenanceEntry, SettlementBatch};
 
+use crate::app_fee::{distribute_revenue_with_app_fee, AppFee};
 use crate::distribution::distribute_revenue;
-use crate::settlement::create_settlement_batch;
+use crate::settlement::{create_settlement_batch, create_settlement_batch_with_app_fee};
 
 /// Trait for revenue distribution and settlement batch creation.
 ///
@@ -65,6 +66,43 @@ impl Distributor for DefaultDistributor {
     }
 }
 
+/// Distributor that takes an app-level platform fee before the protocol
+/// distribution.
+///
+/// See [`distribute_revenue_with_app_fee`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppFeeDistributor {
+    /// Fee taken from each payment.
+    pub app_fee: AppFee,
+}
+
+impl AppFeeDistributor {
+    /// Create a distributor charging the given app fee.
+    pub fn new(app_fee: AppFee) -> Self {
+        Self { app_fee }
+    }
+}
+
+impl Distributor for AppFeeDistributor {
+    fn distribute(
+        &self,
+        payment: &Payment,
+        provenance: Option<&[ProvenanceEntry]>,
+    ) -> Vec<Distribution> {
+        let prov = provenance.unwrap_or(&payment.provenance);
+        distribute_revenue_with_app_fee(
+            payment.amount,
+            &payment.recipient,
+            prov,
+            Some(&self.app_fee),
+        )
+    }
+
+    fn calculate_batch(&self, payments: &[Payment]) -> SettlementBatch {
+        create_settlement_batch_with_app_fee(payments, Some(&self.app_fee))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,6 +200,27 @@ mod tests {
         assert!(batch.is_empty());
     }
 
+    #[test]
+    fn test_app_fee_distributor() {
+        let app = test_peer_id();
+        let distributor = AppFeeDistributor::new(AppFee::new(app, 500).unwrap());
+        let owner = test_peer_id();
+        let payment = test_payment(owner, vec![]);
+
+        let distributions = distributor.distribute(&payment, None);
+        assert_eq!(distributions.len(), 2);
+        assert!(distributions
+            .iter()
+            .any(|d| d.recipient == app && d.amount == 5));
+
+        let payment_id = payment.id;
+        let batch = distributor.calculate_batch(&[payment]);
+        assert_eq!(batch.total_amount(), 100);
+        let app_entry = batch.entries.iter().find(|e| e.recipient == app).unwrap();
+        assert_eq!(app_entry.amount, 5);
+        assert_eq!(app_entry.payment_ids, vec![payment_id]);
+    }
+
     #[test]
     fn test_default_distributor_default() {
         let distributor = DefaultDistributor;
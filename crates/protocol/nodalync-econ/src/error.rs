@@ -3,7 +3,7 @@
 //! This module defines the `EconError` enum used by all economic
 //! functions in this crate as specified in Protocol Specification §10.
 
-use nodalync_types::Amount;
+use nodalync_types::{Amount, ErrorCode};
 use thiserror::Error;
 
 /// Errors that can occur during economic calculations.
@@ -33,6 +33,13 @@ pub enum EconError {
         max: Amount,
     },
 
+    /// A dynamic pricing rule is inconsistent with its base price
+    #[error("invalid pricing rule: {reason}")]
+    InvalidPricingRule {
+        /// What is wrong with the rule
+        reason: String,
+    },
+
     // =========================================================================
     // Distribution Errors (§10.1)
     // =========================================================================
@@ -44,6 +51,15 @@ pub enum EconError {
     #[error("zero payment amount")]
     ZeroPayment,
 
+    /// App fee is zero or above the protocol maximum
+    #[error("app fee of {basis_points} basis points must be between 1 and {max}")]
+    InvalidAppFee {
+        /// The submitted fee
+        basis_points: u32,
+        /// Maximum allowed fee
+        max: u32,
+    },
+
     // =========================================================================
     // Merkle Errors (§10.4)
     // =========================================================================
@@ -63,6 +79,36 @@ pub enum EconError {
     /// Cannot create proof for empty entries
     #[error("cannot create merkle proof for empty entries")]
     EmptyEntries,
+
+    // =========================================================================
+    // Simulation Errors
+    // =========================================================================
+    /// Simulation configuration cannot be run
+    #[error("invalid simulation config: {0}")]
+    InvalidSimulation(String),
+}
+
+impl EconError {
+    /// Get the protocol error code for this error.
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            Self::PriceTooLow { .. }
+            | Self::PriceTooHigh { .. }
+            | Self::InvalidPricingRule { .. } => ErrorCode::InvalidManifest,
+            Self::EmptyProvenance => ErrorCode::InvalidProvenance,
+            Self::ZeroPayment | Self::InvalidAppFee { .. } => ErrorCode::PaymentInvalid,
+            Self::InvalidMerkleProof => ErrorCode::InvalidHash,
+            Self::IndexOutOfBounds { .. } | Self::EmptyEntries | Self::InvalidSimulation(_) => {
+                ErrorCode::InternalError
+            }
+        }
+    }
+}
+
+impl From<&EconError> for ErrorCode {
+    fn from(error: &EconError) -> Self {
+        error.error_code()
+    }
 }
 
 /// Result type for economic operations.
@@ -83,6 +129,11 @@ mod tests {
         };
         assert!(err.to_string().contains("exceeds maximum"));
 
+        let err = EconError::InvalidPricingRule {
+            reason: "window too short".to_string(),
+        };
+        assert!(err.to_string().contains("window too short"));
+
         let err = EconError::EmptyProvenance;
         assert!(err.to_string().contains("empty provenance"));
 
@@ -98,6 +149,22 @@ mod tests {
         assert_eq!(err, cloned);
     }
 
+    #[test]
+    fn test_error_code_mapping() {
+        assert_eq!(
+            EconError::PriceTooLow { price: 0, min: 1 }.error_code(),
+            ErrorCode::InvalidManifest
+        );
+        assert_eq!(
+            ErrorCode::from(&EconError::EmptyProvenance),
+            ErrorCode::InvalidProvenance
+        );
+        assert_eq!(
+            EconError::ZeroPayment.error_code(),
+            ErrorCode::PaymentInvalid
+        );
+    }
+
     #[test]
     fn test_error_debug() {
         let err = EconError::InvalidMerkleProof;
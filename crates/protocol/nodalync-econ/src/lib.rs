@@ -4,8 +4,12 @@
 //!
 //! - **Revenue Distribution** (§10.1): Split payments between owner and root contributors
 //! - **Price Validation** (§10.3): Validate prices against protocol constraints
+//! - **Price Suggestions**: Suggest a price range from comparable content and demand
 //! - **Settlement Batching** (§10.4): Create batches for on-chain settlement
+//! - **App Fees**: Optional platform fee taken by the embedding application
+//! - **Referral Cuts**: Owner-offered cut paid to the node that referred a query
 //! - **Merkle Proofs**: Allow recipients to verify their inclusion in batches
+//! - **Simulation**: Replay randomized payments and check the invariants above
 //!
 //! # Key Design Decision
 //!
@@ -55,12 +59,16 @@
 //! When the owner is also a root contributor, they receive both the synthesis
 //! fee and their proportional root share.
 
+pub mod app_fee;
 pub mod distribution;
 pub mod distributor;
 pub mod error;
 pub mod merkle;
 pub mod price;
+pub mod pricing;
+pub mod referral;
 pub mod settlement;
+pub mod simulation;
 
 // Re-export main types and functions
 pub use error::{EconError, EconResult};
@@ -68,20 +76,35 @@ pub use error::{EconError, EconResult};
 // Distribution functions
 pub use distribution::{calculate_root_pool, calculate_synthesis_fee, distribute_revenue};
 
+// App-level platform fees
+pub use app_fee::{distribute_revenue_with_app_fee, AppFee};
+
+// Referral cuts
+pub use referral::{distribute_revenue_with_referral, ReferralCut};
+
 // Price validation
-pub use price::{is_valid_price, validate_price};
+pub use price::{is_valid_price, validate_price, validate_pricing_rule};
+
+// Price suggestions
+pub use pricing::{suggest_price, DemandStats, PricePoint, PriceSuggestion};
 
 // Settlement functions
-pub use settlement::{calculate_pending_total, create_settlement_batch, should_settle};
+pub use settlement::{
+    calculate_pending_total, create_settlement_batch, create_settlement_batch_with_app_fee,
+    create_settlement_batch_with_referral, should_settle,
+};
 
 // Merkle functions
 pub use merkle::{
-    compute_batch_id, compute_merkle_root, create_merkle_proof, hash_settlement_entry,
-    verify_merkle_proof, MerkleProof,
+    compute_batch_id, compute_merkle_root, compute_provenance_root, create_merkle_proof,
+    hash_provenance_entry, hash_settlement_entry, verify_merkle_proof, MerkleProof,
 };
 
 // Distributor trait and implementations
-pub use distributor::{DefaultDistributor, Distributor};
+pub use distributor::{AppFeeDistributor, DefaultDistributor, Distributor};
+
+// Simulation
+pub use simulation::{simulate, SimulationConfig, SimulationReport, Violation};
 
 #[cfg(test)]
 mod tests {
@@ -2,9 +2,12 @@
 //!
 //! This module implements merkle tree construction and proof verification
 //! for settlement batches, allowing recipients to verify their inclusion.
+//! It also computes the provenance root that content attestations record
+//! on-chain (§12.2).
 
 use nodalync_crypto::Hash;
-use nodalync_types::SettlementEntry;
+use nodalync_types::{ProvenanceEntry, SettlementEntry};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
 use crate::error::{EconError, EconResult};
@@ -93,8 +96,44 @@ pub fn compute_merkle_root(entries: &[SettlementEntry]) -> Hash {
     }
 
     // Compute leaf hashes
-    let mut hashes: Vec<Hash> = entries.iter().map(hash_settlement_entry).collect();
+    let hashes: Vec<Hash> = entries.iter().map(hash_settlement_entry).collect();
+    merkle_root_of(hashes)
+}
+
+/// Hash a provenance entry for use as a merkle leaf.
+///
+/// Covers the source hash, owner and weight. Visibility is left out, as it
+/// can change after the content is attested.
+pub fn hash_provenance_entry(entry: &ProvenanceEntry) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([DOMAIN_MERKLE_LEAF]);
+    hasher.update(entry.hash.0);
+    hasher.update(entry.owner.0);
+    hasher.update(entry.weight.to_be_bytes());
 
+    let result: [u8; 32] = hasher.finalize().into();
+    Hash(result)
+}
+
+/// Compute the provenance root of a content's `root_L0L1` entries.
+///
+/// This is the root recorded by on-chain attestations. Leaves are ordered
+/// by source hash, so the root doesn't depend on the order of the entries.
+///
+/// # Returns
+/// The merkle root hash, or a zero hash for empty entries
+pub fn compute_provenance_root(entries: &[ProvenanceEntry]) -> Hash {
+    if entries.is_empty() {
+        return Hash([0u8; 32]);
+    }
+
+    let mut sorted: Vec<&ProvenanceEntry> = entries.iter().collect();
+    sorted.sort_by(|a, b| a.hash.0.cmp(&b.hash.0));
+    merkle_root_of(sorted.into_iter().map(hash_provenance_entry).collect())
+}
+
+/// Fold non-empty leaf hashes into their merkle root.
+fn merkle_root_of(mut hashes: Vec<Hash>) -> Hash {
     // Build tree bottom-up
     while hashes.len() > 1 {
         let mut next_level = Vec::new();
@@ -137,7 +176,7 @@ pub fn compute_batch_id(entries: &[SettlementEntry]) -> Hash {
 }
 
 /// A merkle proof for an entry in a settlement batch.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MerkleProof {
     /// Sibling hashes along the path to the root
     pub siblings: Vec<Hash>,
@@ -492,4 +531,30 @@ mod tests {
         // Proof should fail for tampered entry
         assert!(!verify_merkle_proof(&root, &tampered, &proof));
     }
+
+    #[test]
+    fn test_provenance_root() {
+        use nodalync_types::Visibility;
+
+        assert_eq!(compute_provenance_root(&[]), Hash([0u8; 32]));
+
+        let owner = test_peer_id();
+        let a = ProvenanceEntry::new(test_hash(b"a"), owner, Visibility::Shared);
+        let b = ProvenanceEntry::with_weight(test_hash(b"b"), owner, Visibility::Shared, 2);
+        let root = compute_provenance_root(&[a.clone(), b.clone()]);
+
+        // Independent of entry order and visibility
+        assert_eq!(compute_provenance_root(&[b.clone(), a.clone()]), root);
+        let mut private = a.clone();
+        private.visibility = Visibility::Private;
+        assert_eq!(compute_provenance_root(&[private, b.clone()]), root);
+
+        // Weights and owners are covered
+        let mut heavier = a.clone();
+        heavier.weight = 3;
+        assert_ne!(compute_provenance_root(&[heavier, b.clone()]), root);
+        let mut other_owner = a;
+        other_owner.owner = test_peer_id();
+        assert_ne!(compute_provenance_root(&[other_owner, b]), root);
+    }
 }
@@ -1,8 +1,11 @@
 //! Price validation (§10.3).
 //!
-//! This module implements price validation against protocol constraints.
+//! This module implements price validation against protocol constraints,
+//! for fixed prices and dynamic pricing rules.
 
-use nodalync_types::{Amount, MAX_PRICE, MIN_PRICE};
+use nodalync_types::{
+    Amount, PricingRule, MAX_PRICE, MAX_SURGE_STEP_BPS, MIN_PRICE, MIN_SURGE_WINDOW_MS,
+};
 
 use crate::error::{EconError, EconResult};
 
@@ -50,6 +53,55 @@ pub fn is_valid_price(price: Amount) -> bool {
     (MIN_PRICE..=MAX_PRICE).contains(&price)
 }
 
+/// Validate a dynamic pricing rule for content with a base price.
+///
+/// The base price is the rule's floor, so it must be a valid, non-zero
+/// price: free content can't be dynamically priced. The rule's highest
+/// price must be a valid price above the base, surge windows must be at
+/// least `MIN_SURGE_WINDOW_MS` with a step of 1..=`MAX_SURGE_STEP_BPS`, and
+/// a decay must take some time.
+///
+/// Rules in manifests from other peers are checked the same way before
+/// their prices are used.
+pub fn validate_pricing_rule(base: Amount, rule: &PricingRule) -> EconResult<()> {
+    let invalid = |reason: &str| EconError::InvalidPricingRule {
+        reason: reason.to_string(),
+    };
+
+    if base == 0 {
+        return Err(invalid("free content has no price to adjust"));
+    }
+    validate_price(base)?;
+    validate_price(rule.max_price())?;
+    if rule.max_price() <= base {
+        return Err(invalid("highest price must be above the base price"));
+    }
+
+    match rule {
+        PricingRule::Surge {
+            window_ms,
+            step_bps,
+            ..
+        } => {
+            if *window_ms < MIN_SURGE_WINDOW_MS {
+                return Err(invalid("surge window is shorter than a minute"));
+            }
+            if *step_bps == 0 {
+                return Err(invalid("surge step must be above zero"));
+            }
+            if *step_bps > MAX_SURGE_STEP_BPS {
+                return Err(invalid("surge step is above the base price"));
+            }
+        }
+        PricingRule::Decay { duration_ms, .. } => {
+            if *duration_ms == 0 {
+                return Err(invalid("decay duration must be above zero"));
+            }
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -114,4 +166,62 @@ mod tests {
         assert!(!is_valid_price(0));
         assert!(!is_valid_price(MAX_PRICE + 1));
     }
+
+    #[test]
+    fn test_validate_pricing_rule() {
+        let surge = PricingRule::Surge {
+            max_price: 500,
+            window_ms: MIN_SURGE_WINDOW_MS,
+            step_bps: 100,
+        };
+        let decay = PricingRule::Decay {
+            start_price: 500,
+            starts_at: 0,
+            duration_ms: 1_000,
+        };
+        assert!(validate_pricing_rule(100, &surge).is_ok());
+        assert!(validate_pricing_rule(100, &decay).is_ok());
+
+        // Free content and bounds below the base
+        assert!(validate_pricing_rule(0, &surge).is_err());
+        assert!(validate_pricing_rule(500, &surge).is_err());
+        assert!(validate_pricing_rule(
+            100,
+            &PricingRule::Surge {
+                max_price: MAX_PRICE + 1,
+                window_ms: MIN_SURGE_WINDOW_MS,
+                step_bps: 100,
+            }
+        )
+        .is_err());
+
+        // Degenerate windows, steps and durations
+        for rule in [
+            PricingRule::Surge {
+                max_price: 500,
+                window_ms: 1_000,
+                step_bps: 100,
+            },
+            PricingRule::Surge {
+                max_price: 500,
+                window_ms: MIN_SURGE_WINDOW_MS,
+                step_bps: 0,
+            },
+            PricingRule::Surge {
+                max_price: 500,
+                window_ms: MIN_SURGE_WINDOW_MS,
+                step_bps: MAX_SURGE_STEP_BPS + 1,
+            },
+            PricingRule::Decay {
+                start_price: 500,
+                starts_at: 0,
+                duration_ms: 0,
+            },
+        ] {
+            assert!(matches!(
+                validate_pricing_rule(100, &rule),
+                Err(EconError::InvalidPricingRule { .. })
+            ));
+        }
+    }
 }
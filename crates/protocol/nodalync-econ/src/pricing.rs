@@ -0,0 +1,252 @@
+//! Price suggestions from market data.
+//!
+//! Publishers pick a price without knowing what similar content sells for.
+//! `suggest_price` turns the prices of comparable content, each weighted by
+//! how similar it is, into a range:
+//!
+//! - **Range**: the weighted 25th, 50th and 75th percentiles of the
+//!   comparable prices. Free comparables are ignored.
+//! - **Demand**: when the publisher's own similar content has been previewed
+//!   at least `MIN_DEMAND_PREVIEWS` times, its conversion rate (paid queries
+//!   per preview) is compared with `TARGET_CONVERSION_RATE`. Content that
+//!   converts better than that can charge more, content that converts worse
+//!   should charge less; the range moves by at most `MAX_DEMAND_ADJUSTMENT`
+//!   either way.
+//!
+//! Suggested prices always pass `validate_price`.
+//!
+//! # Example
+//!
+//! ```
+//! use nodalync_econ::pricing::{suggest_price, DemandStats, PricePoint};
+//!
+//! let points = [
+//!     PricePoint::new(100, 1.0),
+//!     PricePoint::new(200, 1.0),
+//!     PricePoint::new(300, 1.0),
+//! ];
+//! let suggestion = suggest_price(&points, &DemandStats::default()).unwrap();
+//! assert_eq!(suggestion.median, 200);
+//! assert!(suggestion.low <= suggestion.median && suggestion.median <= suggestion.high);
+//! ```
+
+use nodalync_types::{Amount, MAX_PRICE, MIN_PRICE};
+use serde::{Deserialize, Serialize};
+
+/// Paid queries per preview that leaves the price unchanged.
+pub const TARGET_CONVERSION_RATE: f64 = 0.2;
+
+/// Previews needed before the conversion rate adjusts the range.
+pub const MIN_DEMAND_PREVIEWS: u64 = 10;
+
+/// Largest fraction the demand adjustment moves the range by.
+pub const MAX_DEMAND_ADJUSTMENT: f64 = 0.25;
+
+/// The price of one piece of comparable content.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PricePoint {
+    /// Query price.
+    pub price: Amount,
+    /// How much the price counts, e.g. its similarity to the new content.
+    pub weight: f64,
+}
+
+impl PricePoint {
+    /// Create a price point.
+    pub fn new(price: Amount, weight: f64) -> Self {
+        Self { price, weight }
+    }
+}
+
+/// Demand for the publisher's own similar content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DemandStats {
+    /// Previews served.
+    pub previews: u64,
+    /// Paid queries served.
+    pub paid_queries: u64,
+}
+
+impl DemandStats {
+    /// Paid queries per preview, once there are enough previews to tell.
+    pub fn conversion_rate(&self) -> Option<f64> {
+        (self.previews >= MIN_DEMAND_PREVIEWS)
+            .then(|| self.paid_queries as f64 / self.previews as f64)
+    }
+
+    /// Factor the range is scaled by, between `1 - MAX_DEMAND_ADJUSTMENT`
+    /// and `1 + MAX_DEMAND_ADJUSTMENT`.
+    pub fn demand_factor(&self) -> f64 {
+        match self.conversion_rate() {
+            Some(rate) => {
+                let relative = (rate - TARGET_CONVERSION_RATE) / TARGET_CONVERSION_RATE;
+                1.0 + (relative * MAX_DEMAND_ADJUSTMENT)
+                    .clamp(-MAX_DEMAND_ADJUSTMENT, MAX_DEMAND_ADJUSTMENT)
+            }
+            None => 1.0,
+        }
+    }
+}
+
+/// A suggested price range.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PriceSuggestion {
+    /// Lower end of the range.
+    pub low: Amount,
+    /// Suggested price.
+    pub median: Amount,
+    /// Upper end of the range.
+    pub high: Amount,
+    /// Paid comparables the range is based on.
+    pub comparables: usize,
+    /// Conversion rate of the publisher's similar content, if known.
+    pub conversion_rate: Option<f64>,
+    /// Factor the demand adjustment scaled the range by.
+    pub demand_factor: f64,
+}
+
+/// Suggest a price range from comparable prices and local demand.
+///
+/// Returns `None` when there is no paid comparable with a positive weight.
+pub fn suggest_price(points: &[PricePoint], demand: &DemandStats) -> Option<PriceSuggestion> {
+    let mut points: Vec<PricePoint> = points
+        .iter()
+        .filter(|p| p.price > 0 && p.weight.is_finite() && p.weight > 0.0)
+        .copied()
+        .collect();
+    if points.is_empty() {
+        return None;
+    }
+    points.sort_by_key(|p| p.price);
+
+    let factor = demand.demand_factor();
+    let price_at = |quantile: f64| adjust(weighted_quantile(&points, quantile), factor);
+
+    Some(PriceSuggestion {
+        low: price_at(0.25),
+        median: price_at(0.5),
+        high: price_at(0.75),
+        comparables: points.len(),
+        conversion_rate: demand.conversion_rate(),
+        demand_factor: factor,
+    })
+}
+
+/// The smallest price whose cumulative weight reaches `quantile` of the
+/// total. `points` must be sorted by price.
+fn weighted_quantile(points: &[PricePoint], quantile: f64) -> Amount {
+    let total: f64 = points.iter().map(|p| p.weight).sum();
+    let target = total * quantile;
+    let mut cumulative = 0.0;
+    for point in points {
+        cumulative += point.weight;
+        if cumulative >= target {
+            return point.price;
+        }
+    }
+    points[points.len() - 1].price
+}
+
+/// Scale a price by the demand factor and keep it valid.
+fn adjust(price: Amount, factor: f64) -> Amount {
+    let adjusted = (price as f64 * factor).round();
+    if adjusted >= MAX_PRICE as f64 {
+        MAX_PRICE
+    } else {
+        (adjusted as Amount).max(MIN_PRICE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price::validate_price;
+
+    #[test]
+    fn test_no_comparables() {
+        let demand = DemandStats::default();
+        assert!(suggest_price(&[], &demand).is_none());
+        // Free and weightless comparables don't count
+        let points = [PricePoint::new(0, 1.0), PricePoint::new(100, 0.0)];
+        assert!(suggest_price(&points, &demand).is_none());
+    }
+
+    #[test]
+    fn test_weighted_range() {
+        let points = [
+            PricePoint::new(1_000, 0.1),
+            PricePoint::new(100, 1.0),
+            PricePoint::new(200, 2.0),
+            PricePoint::new(300, 1.0),
+            PricePoint::new(0, 5.0),
+        ];
+        let suggestion = suggest_price(&points, &DemandStats::default()).unwrap();
+        assert_eq!(suggestion.low, 200);
+        assert_eq!(suggestion.median, 200);
+        assert_eq!(suggestion.high, 300);
+        assert_eq!(suggestion.comparables, 4);
+        assert_eq!(suggestion.conversion_rate, None);
+        assert_eq!(suggestion.demand_factor, 1.0);
+    }
+
+    #[test]
+    fn test_demand_factor() {
+        // Too few previews to judge
+        let demand = DemandStats {
+            previews: 5,
+            paid_queries: 5,
+        };
+        assert_eq!(demand.conversion_rate(), None);
+        assert_eq!(demand.demand_factor(), 1.0);
+
+        // On target
+        let demand = DemandStats {
+            previews: 100,
+            paid_queries: 20,
+        };
+        assert_eq!(demand.demand_factor(), 1.0);
+
+        // Converts twice as well: capped at the maximum increase
+        let demand = DemandStats {
+            previews: 100,
+            paid_queries: 40,
+        };
+        assert_eq!(demand.demand_factor(), 1.0 + MAX_DEMAND_ADJUSTMENT);
+
+        // Nobody buys: capped at the maximum decrease
+        let demand = DemandStats {
+            previews: 100,
+            paid_queries: 0,
+        };
+        assert_eq!(demand.demand_factor(), 1.0 - MAX_DEMAND_ADJUSTMENT);
+
+        let points = [PricePoint::new(400, 1.0)];
+        let suggestion = suggest_price(&points, &demand).unwrap();
+        assert_eq!(suggestion.median, 300);
+        assert_eq!(suggestion.conversion_rate, Some(0.0));
+    }
+
+    #[test]
+    fn test_suggestions_are_valid_prices() {
+        let low_demand = DemandStats {
+            previews: 100,
+            paid_queries: 0,
+        };
+        let high_demand = DemandStats {
+            previews: 100,
+            paid_queries: 100,
+        };
+
+        let cheap = suggest_price(&[PricePoint::new(1, 1.0)], &low_demand).unwrap();
+        assert_eq!(cheap.median, MIN_PRICE);
+
+        let expensive = suggest_price(&[PricePoint::new(MAX_PRICE, 1.0)], &high_demand).unwrap();
+        assert_eq!(expensive.median, MAX_PRICE);
+
+        for suggestion in [cheap, expensive] {
+            for price in [suggestion.low, suggestion.median, suggestion.high] {
+                assert!(validate_price(price).is_ok());
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Referral cuts.
+//!
+//! An owner can offer a cut of each payment to the discovery node (search
+//! node, recommender) that referred the paying query. Like the app fee, the
+//! cut is taken off the top of the payment before the protocol distribution
+//! and paid to the referrer through the same settlement batch as everyone
+//! else.
+
+use std::collections::HashMap;
+
+use nodalync_crypto::{Hash, PeerId};
+use nodalync_types::{Amount, Distribution, ProvenanceEntry, ReferralPolicy};
+
+use crate::app_fee::{distribute_revenue_with_app_fee, AppFee};
+use crate::distribution::distribute_revenue;
+
+/// The referral cut owed on a query: who referred it, and the content's
+/// referral policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReferralCut {
+    /// Peer that referred the query.
+    pub referrer: PeerId,
+    /// The content's referral policy.
+    pub policy: ReferralPolicy,
+}
+
+impl ReferralCut {
+    /// Create a referral cut.
+    pub fn new(referrer: PeerId, policy: ReferralPolicy) -> Self {
+        Self { referrer, policy }
+    }
+
+    /// Calculate the cut on a payment amount (rounded down).
+    pub fn amount(&self, payment_amount: Amount) -> Amount {
+        self.policy.amount(payment_amount)
+    }
+}
+
+/// Distribute payment revenue, taking an optional app fee and referral cut
+/// first.
+///
+/// Both are computed on the whole payment and taken off the top; the rest
+/// is split by [`distribute_revenue`]. Without a referral this is identical
+/// to [`distribute_revenue_with_app_fee`]. Distributions are aggregated by
+/// recipient and sorted.
+pub fn distribute_revenue_with_referral(
+    payment_amount: Amount,
+    owner: &PeerId,
+    provenance: &[ProvenanceEntry],
+    app_fee: Option<&AppFee>,
+    referral: Option<&ReferralCut>,
+) -> Vec<Distribution> {
+    let Some(referral) = referral else {
+        return distribute_revenue_with_app_fee(payment_amount, owner, provenance, app_fee);
+    };
+
+    let fee = app_fee.map_or(0, |fee| fee.amount(payment_amount));
+    let cut = referral.amount(payment_amount);
+    let mut amounts: HashMap<PeerId, Amount> = HashMap::new();
+    for dist in distribute_revenue(payment_amount - fee - cut, owner, provenance) {
+        *amounts.entry(dist.recipient).or_default() += dist.amount;
+    }
+    if let Some(app_fee) = app_fee {
+        *amounts.entry(app_fee.recipient).or_default() += fee;
+    }
+    *amounts.entry(referral.referrer).or_default() += cut;
+
+    let mut distributions: Vec<Distribution> = amounts
+        .into_iter()
+        .filter(|(_, amount)| *amount > 0)
+        .map(|(recipient, amount)| Distribution::new(recipient, amount, Hash([0u8; 32])))
+        .collect();
+    distributions.sort_by_key(|d| d.recipient.0);
+    distributions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_types::Visibility;
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    fn amount_for(distributions: &[Distribution], peer: &PeerId) -> Amount {
+        distributions
+            .iter()
+            .find(|d| d.recipient == *peer)
+            .map(|d| d.amount)
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn test_referral_taken_with_app_fee() {
+        let owner = test_peer_id();
+        let root = test_peer_id();
+        let app = test_peer_id();
+        let referrer = test_peer_id();
+        let entry = ProvenanceEntry::with_weight(content_hash(b"src"), root, Visibility::Shared, 1);
+        let fee = AppFee::new(app, 1_000).unwrap();
+        let referral = ReferralCut::new(referrer, ReferralPolicy::new(1_000));
+
+        let distributions =
+            distribute_revenue_with_referral(1_000, &owner, &[entry], Some(&fee), Some(&referral));
+
+        // 100 each to the app and referrer; 800 split 5/95
+        assert_eq!(amount_for(&distributions, &app), 100);
+        assert_eq!(amount_for(&distributions, &referrer), 100);
+        assert_eq!(amount_for(&distributions, &owner), 40);
+        assert_eq!(amount_for(&distributions, &root), 760);
+        assert_eq!(
+            distributions.iter().map(|d| d.amount).sum::<Amount>(),
+            1_000
+        );
+    }
+
+    #[test]
+    fn test_no_referral_matches_app_fee_distribution() {
+        let owner = test_peer_id();
+        let root = test_peer_id();
+        let provenance = vec![ProvenanceEntry::with_weight(
+            content_hash(b"src"),
+            root,
+            Visibility::Shared,
+            3,
+        )];
+        let fee = AppFee::new(test_peer_id(), 250).unwrap();
+
+        assert_eq!(
+            distribute_revenue_with_referral(1_234, &owner, &provenance, Some(&fee), None),
+            distribute_revenue_with_app_fee(1_234, &owner, &provenance, Some(&fee))
+        );
+    }
+}
@@ -10,8 +10,9 @@ use nodalync_types::{
     SETTLEMENT_BATCH_THRESHOLD,
 };
 
-use crate::distribution::distribute_revenue;
+use crate::app_fee::AppFee;
 use crate::merkle::{compute_batch_id, compute_merkle_root};
+use crate::referral::{distribute_revenue_with_referral, ReferralCut};
 
 /// Check if settlement should be triggered.
 ///
@@ -54,6 +55,34 @@ pub fn should_settle(pending_total: Amount, last_settlement: Timestamp, now: Tim
 /// # Returns
 /// A settlement batch ready for on-chain processing
 pub fn create_settlement_batch(payments: &[Payment]) -> SettlementBatch {
+    create_settlement_batch_with_app_fee(payments, None)
+}
+
+/// Create a settlement batch, taking an optional app fee from each payment.
+///
+/// Like [`create_settlement_batch`], but each payment is split by
+/// [`distribute_revenue_with_app_fee`](crate::distribute_revenue_with_app_fee),
+/// so the app fee recipient gets its own settlement entry (listing the
+/// payments it was charged on).
+pub fn create_settlement_batch_with_app_fee(
+    payments: &[Payment],
+    app_fee: Option<&AppFee>,
+) -> SettlementBatch {
+    create_settlement_batch_with_referral(payments, app_fee, None)
+}
+
+/// Create a settlement batch, taking an optional app fee and referral cut
+/// from each payment.
+///
+/// Each payment is split by [`distribute_revenue_with_referral`], so the
+/// referrer gets its own settlement entry. The referral applies to every
+/// payment, so batch only payments referred by the same peer under the
+/// same policy.
+pub fn create_settlement_batch_with_referral(
+    payments: &[Payment],
+    app_fee: Option<&AppFee>,
+    referral: Option<&ReferralCut>,
+) -> SettlementBatch {
     if payments.is_empty() {
         return SettlementBatch::default();
     }
@@ -64,8 +93,13 @@ pub fn create_settlement_batch(payments: &[Payment]) -> SettlementBatch {
 
     for payment in payments {
         // Distribute this payment's revenue
-        let distributions =
-            distribute_revenue(payment.amount, &payment.recipient, &payment.provenance);
+        let distributions = distribute_revenue_with_referral(
+            payment.amount,
+            &payment.recipient,
+            &payment.provenance,
+            app_fee,
+            referral,
+        );
 
         for dist in distributions {
             let entry = by_recipient.entry(dist.recipient).or_default();
@@ -118,7 +152,7 @@ pub fn calculate_pending_total(payments: &[Payment]) -> Amount {
 mod tests {
     use super::*;
     use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key, Signature};
-    use nodalync_types::{ProvenanceEntry, Visibility};
+    use nodalync_types::{ProvenanceEntry, ReferralPolicy, Visibility};
 
     fn test_peer_id() -> PeerId {
         let (_, public_key) = generate_identity();
@@ -347,4 +381,45 @@ mod tests {
         // Total should be 300
         assert_eq!(batch.total_amount(), 300);
     }
+
+    #[test]
+    fn test_create_batch_with_app_fee() {
+        let owner = test_peer_id();
+        let app = test_peer_id();
+        let app_fee = AppFee::new(app, 1_000).unwrap();
+
+        let payment1 = test_payment(100, owner, vec![]);
+        let payment2 = test_payment(200, owner, vec![]);
+        let batch = create_settlement_batch_with_app_fee(&[payment1, payment2], Some(&app_fee));
+
+        assert_eq!(batch.total_amount(), 300);
+        let app_entry = batch.entries.iter().find(|e| e.recipient == app).unwrap();
+        assert_eq!(app_entry.amount, 30);
+
+        // Without a fee the batch matches the protocol batch
+        let payments = vec![test_payment(100, owner, vec![])];
+        assert_eq!(
+            create_settlement_batch_with_app_fee(&payments, None),
+            create_settlement_batch(&payments)
+        );
+    }
+
+    #[test]
+    fn test_create_batch_with_referral() {
+        let owner = test_peer_id();
+        let referrer = test_peer_id();
+        let referral = ReferralCut::new(referrer, ReferralPolicy::new(500));
+
+        let payments = vec![test_payment(1_000, owner, vec![])];
+        let batch = create_settlement_batch_with_referral(&payments, None, Some(&referral));
+
+        assert_eq!(batch.total_amount(), 1_000);
+        let entry = batch
+            .entries
+            .iter()
+            .find(|e| e.recipient == referrer)
+            .unwrap();
+        assert_eq!(entry.amount, 50);
+        assert_eq!(entry.payment_ids, vec![payments[0].id]);
+    }
 }
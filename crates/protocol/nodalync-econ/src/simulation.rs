@@ -0,0 +1,757 @@
+//! Deterministic simulation of revenue distribution and settlement.
+//!
+//! Replays many randomized query payments across a synthetic provenance
+//! graph and checks every result against the invariants of §10:
+//!
+//! - **Conservation**: each payment is distributed in full, and each
+//!   settlement batch pays out exactly what its payments total.
+//! - **Fairness**: each root contributor receives its proportional share of
+//!   the root pool, short by less than one tinybar per unit of weight, and
+//!   the owner additionally receives the synthesis fee and the rounding dust.
+//! - **Batch correctness**: batch entries match the per-payment
+//!   distributions, are sorted by unique recipient, and every entry's merkle
+//!   proof verifies against the batch root.
+//!
+//! The graph mirrors how content is built: every source is its own root
+//! with weight 1, and derived content merges the roots of its inputs with
+//! weight accumulation, so weights grow where provenance paths overlap.
+//!
+//! Randomness comes from a small built-in SplitMix64 generator rather than
+//! an external crate, so a seed produces the same report on every platform
+//! and across dependency upgrades.
+//!
+//! # Example
+//!
+//! ```
+//! use nodalync_econ::simulation::{simulate, SimulationConfig};
+//!
+//! let config = SimulationConfig {
+//!     payments: 500,
+//!     ..SimulationConfig::default()
+//! };
+//! let report = simulate(&config).unwrap();
+//! assert!(report.is_ok());
+//! assert_eq!(report.total_paid, report.total_distributed);
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use nodalync_crypto::{content_hash, Hash, PeerId, Signature};
+use nodalync_types::{Amount, Distribution, Payment, ProvenanceEntry, Visibility};
+
+use crate::distribution::{calculate_synthesis_fee, distribute_revenue};
+use crate::error::{EconError, EconResult};
+use crate::merkle::{compute_merkle_root, create_merkle_proof, verify_merkle_proof};
+use crate::price::validate_price;
+use crate::settlement::{calculate_pending_total, create_settlement_batch};
+
+/// Parameters for a simulation run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulationConfig {
+    /// Seed for the random generator.
+    pub seed: u64,
+    /// Number of distinct peers owning content.
+    pub contributors: usize,
+    /// Number of source (L0) content items.
+    pub sources: usize,
+    /// Number of derived (L3) content items.
+    pub derived: usize,
+    /// Maximum number of inputs a derived item is built from.
+    pub max_inputs: usize,
+    /// Number of query payments to replay.
+    pub payments: usize,
+    /// Smallest payment amount (tinybars).
+    pub min_amount: Amount,
+    /// Largest payment amount (tinybars).
+    pub max_amount: Amount,
+    /// Number of payments per settlement batch.
+    pub batch_size: usize,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            contributors: 50,
+            sources: 200,
+            derived: 100,
+            max_inputs: 5,
+            payments: 10_000,
+            min_amount: 1,
+            max_amount: 100_000_000, // 1 HBAR
+            batch_size: 100,
+        }
+    }
+}
+
+impl SimulationConfig {
+    /// Check that the configuration can be run.
+    pub fn validate(&self) -> EconResult<()> {
+        let invalid = |msg: &str| Err(EconError::InvalidSimulation(msg.to_string()));
+        if self.contributors == 0 {
+            return invalid("contributors must be at least 1");
+        }
+        if self.sources == 0 {
+            return invalid("sources must be at least 1");
+        }
+        if self.derived > 0 && self.max_inputs == 0 {
+            return invalid("max_inputs must be at least 1 when derived content is simulated");
+        }
+        if self.batch_size == 0 {
+            return invalid("batch_size must be at least 1");
+        }
+        if self.min_amount > self.max_amount {
+            return invalid("min_amount must not exceed max_amount");
+        }
+        validate_price(self.min_amount)?;
+        validate_price(self.max_amount)
+    }
+}
+
+/// An invariant broken during a simulation run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// The distributions of a payment do not add up to its amount.
+    ValueNotConserved {
+        /// Index of the payment.
+        payment: usize,
+        /// Payment amount.
+        amount: Amount,
+        /// Sum of the distributions.
+        distributed: Amount,
+    },
+    /// A recipient received an amount outside its fair bounds.
+    OutsideFairBounds {
+        /// Index of the payment.
+        payment: usize,
+        /// The recipient.
+        recipient: PeerId,
+        /// Smallest acceptable amount.
+        min: Amount,
+        /// Largest acceptable amount.
+        max: Amount,
+        /// Amount actually received.
+        actual: Amount,
+    },
+    /// A peer that is neither the owner nor a root contributor was paid.
+    UnexpectedRecipient {
+        /// Index of the payment.
+        payment: usize,
+        /// The recipient.
+        recipient: PeerId,
+    },
+    /// A settlement batch does not match its payments.
+    BatchMismatch {
+        /// Index of the batch.
+        batch: usize,
+        /// What was wrong.
+        reason: String,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ValueNotConserved {
+                payment,
+                amount,
+                distributed,
+            } => write!(
+                f,
+                "payment {}: distributed {} of {}",
+                payment, distributed, amount
+            ),
+            Self::OutsideFairBounds {
+                payment,
+                recipient,
+                min,
+                max,
+                actual,
+            } => write!(
+                f,
+                "payment {}: {} received {}, expected {}..={}",
+                payment, recipient, actual, min, max
+            ),
+            Self::UnexpectedRecipient { payment, recipient } => {
+                write!(f, "payment {}: unexpected recipient {}", payment, recipient)
+            }
+            Self::BatchMismatch { batch, reason } => write!(f, "batch {}: {}", batch, reason),
+        }
+    }
+}
+
+/// Totals for one recipient over a simulation run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecipientSummary {
+    /// The recipient.
+    pub peer_id: PeerId,
+    /// Total amount received.
+    pub received: Amount,
+    /// Synthesis fees received as content owner.
+    pub synthesis_fees: Amount,
+    /// Exact proportional share of the root pools, rounded down per payment.
+    pub fair_share: Amount,
+    /// Number of payments the recipient was paid from.
+    pub payments: u64,
+}
+
+impl RecipientSummary {
+    fn new(peer_id: PeerId) -> Self {
+        Self {
+            peer_id,
+            received: 0,
+            synthesis_fees: 0,
+            fair_share: 0,
+            payments: 0,
+        }
+    }
+}
+
+/// Result of a simulation run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulationReport {
+    /// The configuration that was run.
+    pub config: SimulationConfig,
+    /// Largest number of roots of any content item.
+    pub max_roots: usize,
+    /// Largest accumulated weight of any root.
+    pub max_root_weight: u32,
+    /// Sum of all payment amounts.
+    pub total_paid: Amount,
+    /// Sum of all distributions.
+    pub total_distributed: Amount,
+    /// Sum of all settlement batch entries.
+    pub total_settled: Amount,
+    /// Total synthesis fees paid to owners.
+    pub synthesis_fees: Amount,
+    /// Total root pools shared among root contributors.
+    pub root_pool: Amount,
+    /// Total rounding dust paid to owners.
+    pub rounding_dust: Amount,
+    /// Largest rounding dust of a single payment.
+    pub max_dust: Amount,
+    /// Number of settlement batches created.
+    pub batches: usize,
+    /// Per-recipient totals, largest first.
+    pub recipients: Vec<RecipientSummary>,
+    /// Broken invariants, empty if the run was clean.
+    pub violations: Vec<Violation>,
+}
+
+impl SimulationReport {
+    /// Check whether every invariant held.
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Gini coefficient of the amounts received (0 = perfectly equal).
+    pub fn gini(&self) -> f64 {
+        let n = self.recipients.len();
+        if n == 0 || self.total_distributed == 0 {
+            return 0.0;
+        }
+        let mut amounts: Vec<f64> = self.recipients.iter().map(|r| r.received as f64).collect();
+        amounts.sort_by(|a, b| a.total_cmp(b));
+        let weighted: f64 = amounts
+            .iter()
+            .enumerate()
+            .map(|(i, a)| (i + 1) as f64 * a)
+            .sum();
+        let total: f64 = amounts.iter().sum();
+        (2.0 * weighted) / (n as f64 * total) - (n as f64 + 1.0) / n as f64
+    }
+}
+
+/// Run a simulation.
+///
+/// Builds the provenance graph, replays `config.payments` payments against
+/// randomly chosen content, checks every distribution, and settles the
+/// payments in batches of `config.batch_size`, checking every batch.
+pub fn simulate(config: &SimulationConfig) -> EconResult<SimulationReport> {
+    config.validate()?;
+
+    let mut rng = SplitMix64::new(config.seed);
+    let peers: Vec<PeerId> = (0..config.contributors)
+        .map(|i| synthetic_peer(config.seed, i))
+        .collect();
+    let content = build_graph(config, &peers, &mut rng);
+
+    let mut report = SimulationReport {
+        config: config.clone(),
+        max_roots: content.iter().map(|c| c.roots.len()).max().unwrap_or(0),
+        max_root_weight: content
+            .iter()
+            .flat_map(|c| c.roots.iter().map(|r| r.weight))
+            .max()
+            .unwrap_or(0),
+        total_paid: 0,
+        total_distributed: 0,
+        total_settled: 0,
+        synthesis_fees: 0,
+        root_pool: 0,
+        rounding_dust: 0,
+        max_dust: 0,
+        batches: 0,
+        recipients: Vec::new(),
+        violations: Vec::new(),
+    };
+    let mut recipients: HashMap<PeerId, RecipientSummary> = HashMap::new();
+    let mut pending: Vec<Payment> = Vec::with_capacity(config.batch_size);
+    let mut expected: HashMap<PeerId, Amount> = HashMap::new();
+
+    for index in 0..config.payments {
+        let item = &content[rng.below(content.len())];
+        let amount = rng.between(config.min_amount, config.max_amount);
+        let querier = rng.below(peers.len());
+
+        let distributions = distribute_revenue(amount, &item.owner, &item.roots);
+        report.violations.extend(check_distribution(
+            index,
+            amount,
+            &item.owner,
+            &item.roots,
+            &distributions,
+        ));
+
+        // Totals
+        let fee = calculate_synthesis_fee(amount);
+        let root_pool = amount - fee;
+        let total_weight: u64 = item.roots.iter().map(|r| r.weight as u64).sum();
+        let dust = root_pool % total_weight;
+        report.total_paid += amount;
+        report.synthesis_fees += fee;
+        report.root_pool += root_pool;
+        report.rounding_dust += dust;
+        report.max_dust = report.max_dust.max(dust);
+
+        recipients
+            .entry(item.owner)
+            .or_insert_with(|| RecipientSummary::new(item.owner))
+            .synthesis_fees += fee;
+        for (peer, weight) in weights_by_peer(&item.roots) {
+            recipients
+                .entry(peer)
+                .or_insert_with(|| RecipientSummary::new(peer))
+                .fair_share += fair_share(root_pool, weight, total_weight);
+        }
+        for dist in &distributions {
+            report.total_distributed += dist.amount;
+            let summary = recipients
+                .entry(dist.recipient)
+                .or_insert_with(|| RecipientSummary::new(dist.recipient));
+            summary.received += dist.amount;
+            summary.payments += 1;
+            *expected.entry(dist.recipient).or_default() += dist.amount;
+        }
+
+        pending.push(Payment::new(
+            tagged_hash(b"sim-payment", config.seed, index as u64),
+            tagged_hash(b"sim-channel", config.seed, querier as u64),
+            amount,
+            item.owner,
+            item.hash,
+            item.roots.clone(),
+            index as u64,
+            Signature([0u8; 64]),
+        ));
+
+        if pending.len() == config.batch_size || index + 1 == config.payments {
+            let (settled, violations) = check_batch(report.batches, &pending, &expected);
+            report.total_settled += settled;
+            report.violations.extend(violations);
+            report.batches += 1;
+            pending.clear();
+            expected.clear();
+        }
+    }
+
+    let mut recipients: Vec<RecipientSummary> = recipients.into_values().collect();
+    recipients.sort_by(|a, b| {
+        b.received
+            .cmp(&a.received)
+            .then_with(|| a.peer_id.0.cmp(&b.peer_id.0))
+    });
+    report.recipients = recipients;
+
+    Ok(report)
+}
+
+/// A content item in the synthetic graph.
+struct SimContent {
+    hash: Hash,
+    owner: PeerId,
+    /// Root L0/L1 entries with accumulated weights.
+    roots: Vec<ProvenanceEntry>,
+}
+
+/// Build the synthetic provenance graph.
+///
+/// Sources come first; each derived item draws its inputs from everything
+/// built before it, including earlier derived items.
+fn build_graph(
+    config: &SimulationConfig,
+    peers: &[PeerId],
+    rng: &mut SplitMix64,
+) -> Vec<SimContent> {
+    let mut content: Vec<SimContent> = Vec::with_capacity(config.sources + config.derived);
+
+    for i in 0..config.sources {
+        let hash = tagged_hash(b"sim-source", config.seed, i as u64);
+        let owner = peers[rng.below(peers.len())];
+        content.push(SimContent {
+            hash,
+            owner,
+            roots: vec![ProvenanceEntry::with_weight(
+                hash,
+                owner,
+                Visibility::Shared,
+                1,
+            )],
+        });
+    }
+
+    for i in 0..config.derived {
+        let hash = tagged_hash(b"sim-derived", config.seed, i as u64);
+        let owner = peers[rng.below(peers.len())];
+        let inputs = 1 + rng.below(config.max_inputs);
+
+        let mut roots: Vec<ProvenanceEntry> = Vec::new();
+        for _ in 0..inputs {
+            let input = &content[rng.below(content.len())];
+            for root in &input.roots {
+                match roots.iter_mut().find(|r| r.hash == root.hash) {
+                    Some(existing) => existing.weight += root.weight,
+                    None => roots.push(root.clone()),
+                }
+            }
+        }
+        content.push(SimContent { hash, owner, roots });
+    }
+
+    content
+}
+
+/// Check one payment's distributions against conservation and fairness.
+fn check_distribution(
+    payment: usize,
+    amount: Amount,
+    owner: &PeerId,
+    roots: &[ProvenanceEntry],
+    distributions: &[Distribution],
+) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    let distributed: Amount = distributions.iter().map(|d| d.amount).sum();
+    if distributed != amount {
+        violations.push(Violation::ValueNotConserved {
+            payment,
+            amount,
+            distributed,
+        });
+    }
+
+    let mut received: HashMap<PeerId, Amount> = HashMap::new();
+    for dist in distributions {
+        *received.entry(dist.recipient).or_default() += dist.amount;
+    }
+
+    let fee = calculate_synthesis_fee(amount);
+    let root_pool = amount - fee;
+    let total_weight: u64 = roots.iter().map(|r| r.weight as u64).sum();
+    let mut weights = weights_by_peer(roots);
+    weights.entry(*owner).or_insert(0);
+
+    for (peer, weight) in &weights {
+        // Rounding down per unit of weight loses less than one tinybar per
+        // unit; only the owner may receive more, as it collects the dust.
+        let fair = fair_share(root_pool, *weight, total_weight);
+        let mut min = (fair + 1).saturating_sub(*weight).min(fair);
+        let mut max = fair;
+        if peer == owner {
+            min += fee;
+            max += fee + total_weight.saturating_sub(1);
+        }
+        let actual = received.get(peer).copied().unwrap_or(0);
+        if actual < min || actual > max {
+            violations.push(Violation::OutsideFairBounds {
+                payment,
+                recipient: *peer,
+                min,
+                max,
+                actual,
+            });
+        }
+    }
+
+    for recipient in received.keys() {
+        if !weights.contains_key(recipient) {
+            violations.push(Violation::UnexpectedRecipient {
+                payment,
+                recipient: *recipient,
+            });
+        }
+    }
+
+    violations
+}
+
+/// Settle a batch of payments and check it against the expected
+/// per-recipient totals. Returns the amount settled and any violations.
+fn check_batch(
+    batch: usize,
+    payments: &[Payment],
+    expected: &HashMap<PeerId, Amount>,
+) -> (Amount, Vec<Violation>) {
+    let settlement = create_settlement_batch(payments);
+    let entries = &settlement.entries;
+    let mut reasons = Vec::new();
+
+    let settled: Amount = entries.iter().map(|e| e.amount).sum();
+    let pending = calculate_pending_total(payments);
+    if settled != pending {
+        reasons.push(format!(
+            "entries total {} but payments total {}",
+            settled, pending
+        ));
+    }
+
+    if entries
+        .windows(2)
+        .any(|w| w[0].recipient.0 >= w[1].recipient.0)
+    {
+        reasons.push("entries are not sorted by unique recipient".to_string());
+    }
+
+    if entries.len() != expected.len() {
+        reasons.push(format!(
+            "{} entries but {} recipients were paid",
+            entries.len(),
+            expected.len()
+        ));
+    }
+    for entry in entries {
+        let want = expected.get(&entry.recipient).copied().unwrap_or(0);
+        if entry.amount != want {
+            reasons.push(format!(
+                "{} settles {} but was paid {}",
+                entry.recipient, entry.amount, want
+            ));
+        }
+    }
+
+    if settlement.merkle_root != compute_merkle_root(entries) {
+        reasons.push("merkle root does not match entries".to_string());
+    }
+    for (i, entry) in entries.iter().enumerate() {
+        let verified = create_merkle_proof(entries, i)
+            .map(|proof| verify_merkle_proof(&settlement.merkle_root, entry, &proof))
+            .unwrap_or(false);
+        if !verified {
+            reasons.push(format!(
+                "merkle proof for {} does not verify",
+                entry.recipient
+            ));
+        }
+    }
+
+    let batch_ids: HashSet<Hash> = payments.iter().map(|p| p.id).collect();
+    let settled_ids: HashSet<Hash> = entries
+        .iter()
+        .flat_map(|e| e.payment_ids.iter().copied())
+        .collect();
+    if settled_ids != batch_ids {
+        reasons.push("payment ids in entries do not match the batch".to_string());
+    }
+
+    let violations = reasons
+        .into_iter()
+        .map(|reason| Violation::BatchMismatch { batch, reason })
+        .collect();
+    (settled, violations)
+}
+
+/// Sum root weights per contributing peer.
+fn weights_by_peer(roots: &[ProvenanceEntry]) -> HashMap<PeerId, u64> {
+    let mut weights = HashMap::new();
+    for root in roots {
+        *weights.entry(root.owner).or_default() += root.weight as u64;
+    }
+    weights
+}
+
+/// Exact proportional share of `root_pool` for `weight`, rounded down.
+fn fair_share(root_pool: Amount, weight: u64, total_weight: u64) -> Amount {
+    (root_pool as u128 * weight as u128 / total_weight as u128) as Amount
+}
+
+/// Deterministic peer ID for a synthetic contributor.
+fn synthetic_peer(seed: u64, index: usize) -> PeerId {
+    let hash = tagged_hash(b"sim-peer", seed, index as u64);
+    let mut id = [0u8; 20];
+    id.copy_from_slice(&hash.0[..20]);
+    PeerId(id)
+}
+
+/// Deterministic hash for a tagged, seeded index.
+fn tagged_hash(tag: &[u8], seed: u64, index: u64) -> Hash {
+    let mut data = tag.to_vec();
+    data.extend_from_slice(&seed.to_be_bytes());
+    data.extend_from_slice(&index.to_be_bytes());
+    content_hash(&data)
+}
+
+/// SplitMix64 pseudo-random generator.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Value in `0..n` (n must be non-zero).
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Value in `min..=max`.
+    fn between(&mut self, min: u64, max: u64) -> u64 {
+        match (max - min).checked_add(1) {
+            Some(span) => min + self.next_u64() % span,
+            None => self.next_u64(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small_config(seed: u64) -> SimulationConfig {
+        SimulationConfig {
+            seed,
+            contributors: 12,
+            sources: 40,
+            derived: 30,
+            payments: 2_000,
+            batch_size: 64,
+            ..SimulationConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_invariants_hold_over_many_payments() {
+        let report = simulate(&SimulationConfig::default()).unwrap();
+
+        assert!(report.is_ok(), "violations: {:?}", report.violations);
+        assert_eq!(report.total_paid, report.total_distributed);
+        assert_eq!(report.total_paid, report.total_settled);
+        assert_eq!(report.total_paid, report.synthesis_fees + report.root_pool);
+        assert_eq!(report.batches, 100);
+
+        let received: Amount = report.recipients.iter().map(|r| r.received).sum();
+        assert_eq!(received, report.total_paid);
+        // Overlapping provenance paths accumulate weight
+        assert!(report.max_root_weight > 1);
+    }
+
+    #[test]
+    fn test_same_seed_same_report() {
+        let a = simulate(&small_config(7)).unwrap();
+        let b = simulate(&small_config(7)).unwrap();
+        assert_eq!(a, b);
+
+        let c = simulate(&small_config(8)).unwrap();
+        assert_ne!(a.total_paid, c.total_paid);
+    }
+
+    #[test]
+    fn test_recipient_totals_within_fair_bounds() {
+        let report = simulate(&small_config(3)).unwrap();
+        assert!(report.is_ok());
+
+        assert!(report
+            .recipients
+            .iter()
+            .all(|r| r.received >= r.synthesis_fees));
+        // Anything above fees plus the fair share is rounding dust
+        let dust: Amount = report
+            .recipients
+            .iter()
+            .map(|r| (r.received - r.synthesis_fees).saturating_sub(r.fair_share))
+            .sum();
+        assert!(dust <= report.rounding_dust);
+
+        let gini = report.gini();
+        assert!((0.0..1.0).contains(&gini));
+    }
+
+    #[test]
+    fn test_check_distribution_detects_tampering() {
+        let owner = synthetic_peer(0, 0);
+        let alice = synthetic_peer(0, 1);
+        let mallory = synthetic_peer(0, 2);
+        let roots = vec![
+            ProvenanceEntry::with_weight(tagged_hash(b"t", 0, 0), owner, Visibility::Shared, 1),
+            ProvenanceEntry::with_weight(tagged_hash(b"t", 0, 1), alice, Visibility::Shared, 3),
+        ];
+
+        let honest = distribute_revenue(1_000, &owner, &roots);
+        assert!(check_distribution(0, 1_000, &owner, &roots, &honest).is_empty());
+
+        // Skim from alice: total still matches but alice is under her share
+        let mut skimmed = honest.clone();
+        for d in &mut skimmed {
+            if d.recipient == alice {
+                d.amount -= 10;
+            } else {
+                d.amount += 10;
+            }
+        }
+        let violations = check_distribution(0, 1_000, &owner, &roots, &skimmed);
+        assert!(violations.iter().any(|v| matches!(
+            v,
+            Violation::OutsideFairBounds { recipient, .. } if *recipient == alice
+        )));
+
+        // Pay an outsider
+        let mut leaked = honest;
+        leaked.push(Distribution::new(mallory, 1, Hash([0u8; 32])));
+        let violations = check_distribution(0, 1_000, &owner, &roots, &leaked);
+        assert!(violations
+            .iter()
+            .any(|v| matches!(v, Violation::ValueNotConserved { .. })));
+        assert!(violations
+            .iter()
+            .any(|v| matches!(v, Violation::UnexpectedRecipient { .. })));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let config = SimulationConfig {
+            contributors: 0,
+            ..SimulationConfig::default()
+        };
+        assert!(matches!(
+            simulate(&config),
+            Err(EconError::InvalidSimulation(_))
+        ));
+
+        let config = SimulationConfig {
+            min_amount: 0,
+            ..SimulationConfig::default()
+        };
+        assert!(matches!(
+            simulate(&config),
+            Err(EconError::PriceTooLow { .. })
+        ));
+    }
+}
@@ -0,0 +1,766 @@
+//! Session capture of wire messages.
+//!
+//! Where the replay log keeps every encoded message so it can be fed back
+//! through the handlers, a capture is for looking at traffic: which peers
+//! sent what, how much of it failed, and how much money moved. Each
+//! message is stored decoded, as its type and payload, with the time it
+//! was seen, the remote peer and which way it went. `nodalync net inspect`
+//! summarizes a capture.
+//!
+//! # Format
+//!
+//! A capture file starts with the 8-byte magic `NDLCAP01`, followed by
+//! frames. Integers are big-endian.
+//!
+//! | Frame | Layout |
+//! |-------|--------|
+//! | Session (`0x01`) | started_at `u64`, local peer (`u8` length, libp2p peer ID bytes) |
+//! | Message (`0x02`) | timestamp `u64`, direction `u8`, kind `u8`, peer (`u8` length, bytes; 0 if unknown), message type `u16` (0 if undecodable), body (`u32` length, bytes) |
+//!
+//! A session frame is written each time a node opens the capture, so one
+//! file holds every run of the node. The body is the message payload, or
+//! the raw bytes received if the message didn't decode. The file is not
+//! capped; enable capture while investigating, not permanently.
+
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use nodalync_crypto::Timestamp;
+use nodalync_types::{Amount, ErrorCode};
+use nodalync_wire::{
+    decode_message, decode_payload, InvoicePayPayload, MessageType, QueryErrorPayload,
+    QueryRequestPayload,
+};
+use tracing::warn;
+
+use crate::replay::{ReplayDirection, ReplayKind};
+
+/// Magic bytes at the start of every capture file.
+pub const CAPTURE_MAGIC: [u8; 8] = *b"NDLCAP01";
+
+const FRAME_SESSION: u8 = 0x01;
+const FRAME_MESSAGE: u8 = 0x02;
+
+/// Where to write a session capture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureConfig {
+    /// Capture file.
+    pub path: PathBuf,
+}
+
+impl CaptureConfig {
+    /// Capture to `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+/// One captured wire message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureRecord {
+    /// When the message was seen, by the local clock.
+    pub timestamp: Timestamp,
+    /// Which way the message went.
+    pub direction: ReplayDirection,
+    /// How the message travelled.
+    pub kind: ReplayKind,
+    /// The remote libp2p peer, when known.
+    pub peer: Option<libp2p::PeerId>,
+    /// The decoded message type, if the message decoded.
+    pub message_type: Option<MessageType>,
+    /// The message payload, or the raw bytes if the message didn't decode.
+    pub body: Vec<u8>,
+}
+
+impl CaptureRecord {
+    /// Capture an encoded wire message, decoding it for its type and
+    /// payload.
+    pub fn new(
+        direction: ReplayDirection,
+        kind: ReplayKind,
+        data: &[u8],
+        timestamp: Timestamp,
+    ) -> Self {
+        let (message_type, body) = match decode_message(data) {
+            Ok(message) => (Some(message.message_type), message.payload),
+            Err(_) => (None, data.to_vec()),
+        };
+        Self {
+            timestamp,
+            direction,
+            kind,
+            peer: None,
+            message_type,
+            body,
+        }
+    }
+
+    /// Set the remote peer.
+    pub fn with_peer(mut self, peer: libp2p::PeerId) -> Self {
+        self.peer = Some(peer);
+        self
+    }
+
+    /// Payment amounts carried by the message: the payment attached to a
+    /// query, or the one paying an invoice.
+    pub fn payments(&self) -> Vec<Amount> {
+        match self.message_type {
+            Some(MessageType::QueryRequest) => decode_payload::<QueryRequestPayload>(&self.body)
+                .ok()
+                .and_then(|p| p.payment)
+                .map(|p| vec![p.amount])
+                .unwrap_or_default(),
+            Some(MessageType::InvoicePay) => decode_payload::<InvoicePayPayload>(&self.body)
+                .map(|p| vec![p.payment.amount])
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The error the message reports, if it is a query error.
+    pub fn error_code(&self) -> Option<ErrorCode> {
+        match self.message_type {
+            Some(MessageType::QueryError) => decode_payload::<QueryErrorPayload>(&self.body)
+                .ok()
+                .map(|p| p.error_code),
+            _ => None,
+        }
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(FRAME_MESSAGE);
+        out.extend_from_slice(&self.timestamp.to_be_bytes());
+        out.push(match self.direction {
+            ReplayDirection::Inbound => 0,
+            ReplayDirection::Outbound => 1,
+        });
+        out.push(match self.kind {
+            ReplayKind::Request => 0,
+            ReplayKind::Response => 1,
+            ReplayKind::Broadcast => 2,
+        });
+        let peer = self.peer.map(|p| p.to_bytes()).unwrap_or_default();
+        out.push(peer.len() as u8);
+        out.extend_from_slice(&peer);
+        out.extend_from_slice(&self.message_type.map_or(0, |t| t as u16).to_be_bytes());
+        out.extend_from_slice(&(self.body.len() as u32).to_be_bytes());
+        out.extend_from_slice(&self.body);
+    }
+}
+
+/// One run of a node: the messages captured between opening the capture
+/// and closing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureSession {
+    /// When the node opened the capture.
+    pub started_at: Timestamp,
+    /// The capturing node's libp2p peer ID.
+    pub local_peer: Option<libp2p::PeerId>,
+    /// The messages captured, oldest first.
+    pub records: Vec<CaptureRecord>,
+}
+
+/// An open capture, appending messages to the current session.
+#[derive(Debug)]
+pub struct SessionRecorder {
+    file: File,
+}
+
+impl SessionRecorder {
+    /// Open the capture, creating it if needed, and start a new session.
+    ///
+    /// A frame cut short by a crash is dropped, so it doesn't swallow the
+    /// frames after it.
+    pub fn open(
+        config: &CaptureConfig,
+        local_peer: libp2p::PeerId,
+        started_at: Timestamp,
+    ) -> io::Result<Self> {
+        if let Some(parent) = config.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&config.path)?;
+
+        let existing = std::fs::read(&config.path)?;
+        if existing.is_empty() {
+            file.write_all(&CAPTURE_MAGIC)?;
+        } else {
+            let (_, valid) = parse_capture(&existing)?;
+            if valid < existing.len() {
+                file.set_len(valid as u64)?;
+            }
+        }
+
+        let peer = local_peer.to_bytes();
+        let mut frame = Vec::with_capacity(10 + peer.len());
+        frame.push(FRAME_SESSION);
+        frame.extend_from_slice(&started_at.to_be_bytes());
+        frame.push(peer.len() as u8);
+        frame.extend_from_slice(&peer);
+        file.write_all(&frame)?;
+        Ok(Self { file })
+    }
+
+    /// Append a message to the current session.
+    pub fn record(&mut self, record: &CaptureRecord) -> io::Result<()> {
+        let mut frame = Vec::with_capacity(64 + record.body.len());
+        record.encode(&mut frame);
+        self.file.write_all(&frame)
+    }
+}
+
+/// Read every session of a capture, oldest first.
+///
+/// Returns an empty list if the file does not exist, and an error if it
+/// isn't a capture. A frame cut short by a crash, and anything after it,
+/// is skipped with a warning.
+pub fn read_capture(path: &Path) -> io::Result<Vec<CaptureSession>> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let (sessions, valid) = parse_capture(&bytes)?;
+    if valid < bytes.len() {
+        warn!(
+            offset = valid,
+            "Skipping unreadable capture data after the last complete frame"
+        );
+    }
+    Ok(sessions)
+}
+
+/// Parse a capture, returning its sessions and the length of the complete
+/// frames.
+fn parse_capture(bytes: &[u8]) -> io::Result<(Vec<CaptureSession>, usize)> {
+    if !bytes.starts_with(&CAPTURE_MAGIC) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a Nodalync capture file",
+        ));
+    }
+
+    let mut sessions: Vec<CaptureSession> = Vec::new();
+    let mut valid = CAPTURE_MAGIC.len();
+    let mut reader = FrameReader { bytes, pos: valid };
+    while reader.pos < bytes.len() {
+        let parsed = match reader.u8() {
+            Some(FRAME_SESSION) => reader.session().map(|session| sessions.push(session)),
+            Some(FRAME_MESSAGE) => reader.record().map(|record| match sessions.last_mut() {
+                Some(session) => session.records.push(record),
+                // Messages always follow a session frame; start one if not
+                None => sessions.push(CaptureSession {
+                    started_at: record.timestamp,
+                    local_peer: None,
+                    records: vec![record],
+                }),
+            }),
+            _ => None,
+        };
+        if parsed.is_none() {
+            break;
+        }
+        valid = reader.pos;
+    }
+    Ok((sessions, valid))
+}
+
+/// Cursor over capture frames; every read returns `None` past the end.
+struct FrameReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl FrameReader<'_> {
+    fn take(&mut self, len: usize) -> Option<&[u8]> {
+        let end = self.pos.checked_add(len)?;
+        let slice = self.bytes.get(self.pos..end)?;
+        self.pos = end;
+        Some(slice)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4)
+            .map(|b| u32::from_be_bytes(b.try_into().expect("4 bytes")))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take(8)
+            .map(|b| u64::from_be_bytes(b.try_into().expect("8 bytes")))
+    }
+
+    /// A length-prefixed peer ID; `Some(None)` if absent or malformed.
+    fn peer(&mut self) -> Option<Option<libp2p::PeerId>> {
+        let len = self.u8()? as usize;
+        let bytes = self.take(len)?;
+        Some(libp2p::PeerId::from_bytes(bytes).ok())
+    }
+
+    fn session(&mut self) -> Option<CaptureSession> {
+        Some(CaptureSession {
+            started_at: self.u64()?,
+            local_peer: self.peer()?,
+            records: Vec::new(),
+        })
+    }
+
+    fn record(&mut self) -> Option<CaptureRecord> {
+        let timestamp = self.u64()?;
+        let direction = match self.u8()? {
+            0 => ReplayDirection::Inbound,
+            1 => ReplayDirection::Outbound,
+            _ => return None,
+        };
+        let kind = match self.u8()? {
+            0 => ReplayKind::Request,
+            1 => ReplayKind::Response,
+            2 => ReplayKind::Broadcast,
+            _ => return None,
+        };
+        let peer = self.peer()?;
+        let message_type = MessageType::from_u16(self.u16()?).ok();
+        let len = self.u32()? as usize;
+        let body = self.take(len)?.to_vec();
+        Some(CaptureRecord {
+            timestamp,
+            direction,
+            kind,
+            peer,
+            message_type,
+            body,
+        })
+    }
+}
+
+/// Messages of one type, by direction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrafficCount {
+    /// Received from peers.
+    pub inbound: u64,
+    /// Sent by this node.
+    pub outbound: u64,
+}
+
+impl TrafficCount {
+    /// Messages in both directions.
+    pub fn total(&self) -> u64 {
+        self.inbound + self.outbound
+    }
+}
+
+/// Payments seen going one way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PaymentFlow {
+    /// Number of payments.
+    pub payments: u64,
+    /// Total amount paid (in tinybars).
+    pub amount: Amount,
+}
+
+impl PaymentFlow {
+    fn add(&mut self, amount: Amount) {
+        self.payments += 1;
+        self.amount = self.amount.saturating_add(amount);
+    }
+}
+
+/// Traffic with one remote peer during a session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerSummary {
+    /// The remote libp2p peer.
+    pub peer: libp2p::PeerId,
+    /// Messages exchanged with the peer.
+    pub messages: TrafficCount,
+    /// Undecodable messages and query errors exchanged with the peer.
+    pub errors: u64,
+    /// Payments this node sent the peer.
+    pub paid: PaymentFlow,
+    /// Payments the peer sent this node.
+    pub received: PaymentFlow,
+}
+
+/// What happened during a captured session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSummary {
+    /// When the node opened the capture.
+    pub started_at: Timestamp,
+    /// When the last message was captured (`started_at` if none were).
+    pub ended_at: Timestamp,
+    /// The capturing node's libp2p peer ID.
+    pub local_peer: Option<libp2p::PeerId>,
+    /// Every message captured.
+    pub messages: TrafficCount,
+    /// Messages by type, keyed by the type's wire name.
+    pub message_types: BTreeMap<String, TrafficCount>,
+    /// Messages that didn't decode.
+    pub undecodable: u64,
+    /// Query errors by error code name.
+    pub error_codes: BTreeMap<String, u64>,
+    /// Payments this node sent.
+    pub paid: PaymentFlow,
+    /// Payments this node received.
+    pub received: PaymentFlow,
+    /// Traffic per remote peer, busiest first.
+    pub peers: Vec<PeerSummary>,
+}
+
+impl SessionSummary {
+    /// Undecodable messages and query errors.
+    pub fn errors(&self) -> u64 {
+        self.undecodable + self.error_codes.values().sum::<u64>()
+    }
+
+    /// Share of messages that were undecodable or query errors, in `[0, 1]`.
+    pub fn error_rate(&self) -> f64 {
+        match self.messages.total() {
+            0 => 0.0,
+            total => self.errors() as f64 / total as f64,
+        }
+    }
+}
+
+impl CaptureSession {
+    /// Summarize the session's traffic.
+    pub fn summary(&self) -> SessionSummary {
+        let mut summary = SessionSummary {
+            started_at: self.started_at,
+            ended_at: self
+                .records
+                .iter()
+                .map(|r| r.timestamp)
+                .max()
+                .unwrap_or(self.started_at)
+                .max(self.started_at),
+            local_peer: self.local_peer,
+            messages: TrafficCount::default(),
+            message_types: BTreeMap::new(),
+            undecodable: 0,
+            error_codes: BTreeMap::new(),
+            paid: PaymentFlow::default(),
+            received: PaymentFlow::default(),
+            peers: Vec::new(),
+        };
+        let mut peers: BTreeMap<libp2p::PeerId, PeerSummary> = BTreeMap::new();
+
+        for record in &self.records {
+            let inbound = record.direction == ReplayDirection::Inbound;
+            let count = |counts: &mut TrafficCount| {
+                if inbound {
+                    counts.inbound += 1;
+                } else {
+                    counts.outbound += 1;
+                }
+            };
+            count(&mut summary.messages);
+            let type_name = record
+                .message_type
+                .map_or_else(|| "UNDECODABLE".to_string(), |t| t.to_string());
+            count(summary.message_types.entry(type_name).or_default());
+
+            let error_code = record.error_code();
+            let is_error = record.message_type.is_none() || error_code.is_some();
+            if record.message_type.is_none() {
+                summary.undecodable += 1;
+            }
+            if let Some(code) = error_code {
+                *summary.error_codes.entry(code.to_string()).or_default() += 1;
+            }
+            let payments = record.payments();
+            let flow = if inbound {
+                &mut summary.received
+            } else {
+                &mut summary.paid
+            };
+            payments.iter().for_each(|&amount| flow.add(amount));
+
+            let Some(peer) = record.peer else {
+                continue;
+            };
+            let peer_summary = peers.entry(peer).or_insert_with(|| PeerSummary {
+                peer,
+                messages: TrafficCount::default(),
+                errors: 0,
+                paid: PaymentFlow::default(),
+                received: PaymentFlow::default(),
+            });
+            count(&mut peer_summary.messages);
+            if is_error {
+                peer_summary.errors += 1;
+            }
+            let flow = if inbound {
+                &mut peer_summary.received
+            } else {
+                &mut peer_summary.paid
+            };
+            payments.iter().for_each(|&amount| flow.add(amount));
+        }
+
+        summary.peers = peers.into_values().collect();
+        summary
+            .peers
+            .sort_by_key(|p| std::cmp::Reverse(p.messages.total()));
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key, Signature};
+    use nodalync_types::Payment;
+    use nodalync_wire::{create_message, encode_message, encode_payload, PingPayload};
+    use tempfile::TempDir;
+
+    fn message(message_type: MessageType, payload: Vec<u8>) -> Vec<u8> {
+        let (private_key, public_key) = generate_identity();
+        let message = create_message(
+            message_type,
+            payload,
+            peer_id_from_public_key(&public_key),
+            1_000,
+            &private_key,
+        );
+        encode_message(&message).unwrap()
+    }
+
+    fn ping() -> Vec<u8> {
+        message(
+            MessageType::Ping,
+            encode_payload(&PingPayload { nonce: 7 }).unwrap(),
+        )
+    }
+
+    fn paid_query(amount: Amount) -> Vec<u8> {
+        let (_, public_key) = generate_identity();
+        let hash = content_hash(b"content");
+        let payment = Payment::new(
+            content_hash(b"payment"),
+            content_hash(b"channel"),
+            amount,
+            peer_id_from_public_key(&public_key),
+            hash,
+            Vec::new(),
+            1_000,
+            Signature::from_bytes([0u8; 64]),
+        );
+        let request = QueryRequestPayload {
+            hash,
+            query: None,
+            payment: Some(payment),
+            version_spec: None,
+            payment_nonce: 1,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        };
+        message(MessageType::QueryRequest, encode_payload(&request).unwrap())
+    }
+
+    fn query_error(code: ErrorCode) -> Vec<u8> {
+        let error = QueryErrorPayload::new(content_hash(b"content"), code, "nope");
+        message(MessageType::QueryError, encode_payload(&error).unwrap())
+    }
+
+    #[test]
+    fn test_capture_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let config = CaptureConfig::new(temp.path().join("capture.ndlcap"));
+        let local = libp2p::PeerId::random();
+        let peer = libp2p::PeerId::random();
+
+        let ping = CaptureRecord::new(
+            ReplayDirection::Inbound,
+            ReplayKind::Request,
+            &ping(),
+            1_500,
+        )
+        .with_peer(peer);
+        let junk = CaptureRecord::new(
+            ReplayDirection::Inbound,
+            ReplayKind::Broadcast,
+            b"junk",
+            1_600,
+        );
+        assert_eq!(ping.message_type, Some(MessageType::Ping));
+        assert_eq!(
+            decode_payload::<PingPayload>(&ping.body).unwrap(),
+            PingPayload { nonce: 7 }
+        );
+        assert_eq!(junk.message_type, None);
+        assert_eq!(junk.body, b"junk");
+
+        let mut recorder = SessionRecorder::open(&config, local, 1_000).unwrap();
+        recorder.record(&ping).unwrap();
+        recorder.record(&junk).unwrap();
+        drop(recorder);
+
+        // Reopening starts a second session
+        let mut recorder = SessionRecorder::open(&config, local, 5_000).unwrap();
+        recorder.record(&ping).unwrap();
+        drop(recorder);
+
+        let sessions = read_capture(&config.path).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].started_at, 1_000);
+        assert_eq!(sessions[0].local_peer, Some(local));
+        assert_eq!(sessions[0].records, vec![ping.clone(), junk]);
+        assert_eq!(sessions[1].started_at, 5_000);
+        assert_eq!(sessions[1].records, vec![ping]);
+
+        assert!(read_capture(&temp.path().join("missing.ndlcap"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_capture_truncated_frame() {
+        let temp = TempDir::new().unwrap();
+        let config = CaptureConfig::new(temp.path().join("capture.ndlcap"));
+        let local = libp2p::PeerId::random();
+        let record = CaptureRecord::new(
+            ReplayDirection::Outbound,
+            ReplayKind::Request,
+            &ping(),
+            2_000,
+        );
+
+        let mut recorder = SessionRecorder::open(&config, local, 1_000).unwrap();
+        recorder.record(&record).unwrap();
+        drop(recorder);
+        let mut file = OpenOptions::new().append(true).open(&config.path).unwrap();
+        file.write_all(&[FRAME_MESSAGE, 0, 0, 0]).unwrap();
+
+        let sessions = read_capture(&config.path).unwrap();
+        assert_eq!(sessions[0].records.len(), 1);
+
+        // Reopening drops the cut-off frame
+        let mut recorder = SessionRecorder::open(&config, local, 3_000).unwrap();
+        recorder.record(&record).unwrap();
+        let sessions = read_capture(&config.path).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[1].records, vec![record]);
+
+        std::fs::write(&config.path, b"not a capture").unwrap();
+        assert_eq!(
+            read_capture(&config.path).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert!(SessionRecorder::open(&config, local, 4_000).is_err());
+    }
+
+    #[test]
+    fn test_session_summary() {
+        let alice = libp2p::PeerId::random();
+        let bob = libp2p::PeerId::random();
+        let record = |direction, kind, data: &[u8], peer: Option<libp2p::PeerId>| {
+            let record = CaptureRecord::new(direction, kind, data, 2_000);
+            match peer {
+                Some(peer) => record.with_peer(peer),
+                None => record,
+            }
+        };
+        let session = CaptureSession {
+            started_at: 1_000,
+            local_peer: None,
+            records: vec![
+                record(
+                    ReplayDirection::Outbound,
+                    ReplayKind::Request,
+                    &paid_query(300),
+                    Some(alice),
+                ),
+                record(
+                    ReplayDirection::Inbound,
+                    ReplayKind::Response,
+                    &query_error(ErrorCode::NotFound),
+                    Some(alice),
+                ),
+                record(
+                    ReplayDirection::Outbound,
+                    ReplayKind::Request,
+                    &paid_query(200),
+                    Some(alice),
+                ),
+                record(
+                    ReplayDirection::Inbound,
+                    ReplayKind::Request,
+                    &paid_query(50),
+                    Some(bob),
+                ),
+                record(
+                    ReplayDirection::Inbound,
+                    ReplayKind::Broadcast,
+                    b"junk",
+                    None,
+                ),
+            ],
+        };
+
+        let summary = session.summary();
+        assert_eq!(summary.ended_at, 2_000);
+        assert_eq!(
+            summary.messages,
+            TrafficCount {
+                inbound: 3,
+                outbound: 2
+            }
+        );
+        assert_eq!(summary.message_types["QUERY_REQUEST"].total(), 3);
+        assert_eq!(summary.message_types["QUERY_ERROR"].inbound, 1);
+        assert_eq!(summary.message_types["UNDECODABLE"].inbound, 1);
+        assert_eq!(summary.undecodable, 1);
+        assert_eq!(summary.error_codes["NOT_FOUND"], 1);
+        assert_eq!(summary.errors(), 2);
+        assert!((summary.error_rate() - 0.4).abs() < f64::EPSILON);
+        assert_eq!(
+            summary.paid,
+            PaymentFlow {
+                payments: 2,
+                amount: 500
+            }
+        );
+        assert_eq!(
+            summary.received,
+            PaymentFlow {
+                payments: 1,
+                amount: 50
+            }
+        );
+
+        // Broadcasts have no peer; the busiest peer comes first
+        assert_eq!(summary.peers.len(), 2);
+        assert_eq!(summary.peers[0].peer, alice);
+        assert_eq!(summary.peers[0].messages.total(), 3);
+        assert_eq!(summary.peers[0].errors, 1);
+        assert_eq!(summary.peers[0].paid.amount, 500);
+        assert_eq!(summary.peers[1].peer, bob);
+        assert_eq!(summary.peers[1].received.amount, 50);
+
+        let empty = CaptureSession {
+            started_at: 1_000,
+            local_peer: None,
+            records: Vec::new(),
+        };
+        assert_eq!(empty.summary().ended_at, 1_000);
+        assert_eq!(empty.summary().error_rate(), 0.0);
+    }
+}
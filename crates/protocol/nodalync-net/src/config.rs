@@ -2,8 +2,14 @@
 //!
 //! This module defines configuration options for the network layer.
 
+use crate::capture::CaptureConfig;
+use crate::dns_bootstrap::DnsBootstrapConfig;
+use crate::rate_limit::RateLimitConfig;
+use crate::replay::ReplayLogConfig;
 use libp2p::Multiaddr;
 use nodalync_types::constants::{MAX_RETRY_ATTEMPTS, MESSAGE_TIMEOUT_MS};
+use nodalync_types::{normalize_tag, Amount, PeerId};
+use std::path::PathBuf;
 use std::time::Duration;
 
 /// Configuration for the network layer.
@@ -19,6 +25,13 @@ pub struct NetworkConfig {
     /// These should be well-known nodes that help with initial peer discovery.
     pub bootstrap_nodes: Vec<(libp2p::PeerId, Multiaddr)>,
 
+    /// Discover bootstrap nodes from DNS TXT (dnsaddr) records.
+    ///
+    /// Nodes found through DNS or its cache are used instead of
+    /// `bootstrap_nodes`, which remain the fallback.
+    /// Default: None (static list only).
+    pub dns_bootstrap: Option<DnsBootstrapConfig>,
+
     /// Timeout for request-response operations.
     ///
     /// Default: 30 seconds (from spec).
@@ -69,6 +82,90 @@ pub struct NetworkConfig {
     ///
     /// Default: 30 seconds.
     pub idle_connection_timeout: Duration,
+
+    /// Per-IP limits for inbound connections and requests.
+    ///
+    /// Default: None (unlimited).
+    pub rate_limit: Option<RateLimitConfig>,
+
+    /// File to persist DHT records to across restarts.
+    ///
+    /// Default: None (records are kept in memory only).
+    pub dht_record_path: Option<PathBuf>,
+
+    /// Whether to serve inbound request-response messages (previews,
+    /// queries, channels).
+    ///
+    /// Infrastructure nodes that only provide DHT routing and relaying turn
+    /// this off; inbound requests are then dropped.
+    /// Default: true.
+    pub serve_requests: bool,
+
+    /// Onion-style query relaying, both for our own queries and for
+    /// relaying other nodes' queries.
+    ///
+    /// Default: no route and not serving.
+    pub relay: RelayConfig,
+
+    /// Record every wire message sent and received to a replay log.
+    ///
+    /// Default: None (no log).
+    pub replay_log: Option<ReplayLogConfig>,
+
+    /// Capture decoded messages sent and received, per peer, to a session
+    /// capture.
+    ///
+    /// Default: None (no capture).
+    pub capture: Option<CaptureConfig>,
+}
+
+/// Most relays a query may be routed through.
+pub const MAX_RELAY_HOPS: usize = 2;
+
+/// Configuration for relayed queries.
+///
+/// With a route set, queries for content we don't hold go through the
+/// listed relays instead of straight to the provider. Each relay only
+/// learns the hops either side of it, and the provider only sees the last
+/// relay. Relays are paid through payment channels, so we need an open
+/// channel with the first relay, and each relay with the next.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RelayConfig {
+    /// Relays to route our queries through, entry first.
+    ///
+    /// At most [`MAX_RELAY_HOPS`]. Default: empty (query directly).
+    pub route: Vec<PeerId>,
+
+    /// Fee we pay each relay on the route per query.
+    ///
+    /// Default: 0.
+    pub route_fee: Amount,
+
+    /// Whether to relay other nodes' queries.
+    ///
+    /// Default: false.
+    pub serve: bool,
+
+    /// Fee we charge per relayed query when serving.
+    ///
+    /// Default: 0.
+    pub fee: Amount,
+}
+
+impl RelayConfig {
+    /// Route queries through the given relays, paying each `fee`.
+    pub fn with_route(mut self, route: Vec<PeerId>, fee: Amount) -> Self {
+        self.route = route;
+        self.route_fee = fee;
+        self
+    }
+
+    /// Relay other nodes' queries for `fee` each.
+    pub fn with_serve(mut self, fee: Amount) -> Self {
+        self.serve = true;
+        self.fee = fee;
+        self
+    }
 }
 
 impl Default for NetworkConfig {
@@ -76,6 +173,7 @@ impl Default for NetworkConfig {
         Self {
             listen_addresses: vec!["/ip4/0.0.0.0/tcp/0".parse().unwrap()],
             bootstrap_nodes: Vec::new(),
+            dns_bootstrap: None,
             request_timeout: Duration::from_millis(MESSAGE_TIMEOUT_MS),
             max_retries: MAX_RETRY_ATTEMPTS,
             retry_base_delay: Duration::from_millis(100),
@@ -86,6 +184,12 @@ impl Default for NetworkConfig {
             dht_query_timeout: Duration::from_secs(60),
             gossipsub_topic: "/nodalync/announce/1.0.0".to_string(),
             idle_connection_timeout: Duration::from_secs(30),
+            rate_limit: None,
+            dht_record_path: None,
+            serve_requests: true,
+            relay: RelayConfig::default(),
+            replay_log: None,
+            capture: None,
         }
     }
 }
@@ -108,6 +212,12 @@ impl NetworkConfig {
         self
     }
 
+    /// Discover bootstrap nodes from DNS, falling back to the static list.
+    pub fn with_dns_bootstrap(mut self, dns_bootstrap: DnsBootstrapConfig) -> Self {
+        self.dns_bootstrap = Some(dns_bootstrap);
+        self
+    }
+
     /// Set request timeout.
     pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
         self.request_timeout = timeout;
@@ -131,6 +241,51 @@ impl NetworkConfig {
         self.enable_mdns = enable;
         self
     }
+
+    /// Enable per-IP rate limiting.
+    pub fn with_rate_limit(mut self, limits: RateLimitConfig) -> Self {
+        self.rate_limit = Some(limits);
+        self
+    }
+
+    /// Persist DHT records to a file.
+    pub fn with_dht_persistence(mut self, path: impl Into<PathBuf>) -> Self {
+        self.dht_record_path = Some(path.into());
+        self
+    }
+
+    /// Enable or disable serving inbound requests.
+    pub fn with_serve_requests(mut self, serve: bool) -> Self {
+        self.serve_requests = serve;
+        self
+    }
+
+    /// Configure relayed queries.
+    pub fn with_relay(mut self, relay: RelayConfig) -> Self {
+        self.relay = relay;
+        self
+    }
+
+    /// Record wire messages to a replay log.
+    pub fn with_replay_log(mut self, replay_log: ReplayLogConfig) -> Self {
+        self.replay_log = Some(replay_log);
+        self
+    }
+
+    /// Capture decoded messages to a session capture.
+    pub fn with_capture(mut self, capture: CaptureConfig) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// GossipSub topic for announcements of content filed under a tag.
+    ///
+    /// Tag topics hang off the announcement topic, so
+    /// `science/biology` maps to `/nodalync/announce/1.0.0/tag/science/biology`.
+    /// The tag is normalized first; `None` if it is empty.
+    pub fn tag_topic(&self, tag: &str) -> Option<String> {
+        normalize_tag(tag).map(|tag| format!("{}/tag/{}", self.gossipsub_topic, tag))
+    }
 }
 
 #[cfg(test)]
@@ -161,6 +316,16 @@ mod tests {
         assert!(config.enable_mdns);
     }
 
+    #[test]
+    fn test_tag_topic() {
+        let config = NetworkConfig::default();
+        assert_eq!(
+            config.tag_topic("Science/Biology").as_deref(),
+            Some("/nodalync/announce/1.0.0/tag/science/biology")
+        );
+        assert_eq!(config.tag_topic(" "), None);
+    }
+
     #[test]
     fn test_add_bootstrap_node() {
         let peer_id = libp2p::PeerId::random();
@@ -208,6 +373,42 @@ mod tests {
 
         // Idle connection timeout
         assert_eq!(config.idle_connection_timeout, Duration::from_secs(30));
+
+        // No rate limiting or persistence, requests served
+        assert!(config.rate_limit.is_none());
+        assert!(config.dht_record_path.is_none());
+        assert!(config.serve_requests);
+    }
+
+    #[test]
+    fn test_infrastructure_config_builder() {
+        let config = NetworkConfig::new()
+            .with_rate_limit(RateLimitConfig::default())
+            .with_dht_persistence("/tmp/nodalync/dht_records.bin")
+            .with_serve_requests(false);
+
+        assert_eq!(config.rate_limit, Some(RateLimitConfig::default()));
+        assert_eq!(
+            config.dht_record_path,
+            Some(PathBuf::from("/tmp/nodalync/dht_records.bin"))
+        );
+        assert!(!config.serve_requests);
+    }
+
+    #[test]
+    fn test_relay_config_builder() {
+        let relays = vec![PeerId([1u8; 20]), PeerId([2u8; 20])];
+        let config = NetworkConfig::new().with_relay(
+            RelayConfig::default()
+                .with_route(relays.clone(), 10)
+                .with_serve(5),
+        );
+
+        assert_eq!(config.relay.route, relays);
+        assert_eq!(config.relay.route_fee, 10);
+        assert!(config.relay.serve);
+        assert_eq!(config.relay.fee, 5);
+        assert_eq!(NetworkConfig::default().relay, RelayConfig::default());
     }
 
     #[test]
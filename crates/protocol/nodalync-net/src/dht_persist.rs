@@ -0,0 +1,199 @@
+//! DHT record persistence.
+//!
+//! The Kademlia record store lives in memory, so a restarted node forgets
+//! every record it was holding for the network until publishers re-put them.
+//! Long-running infrastructure nodes snapshot their records to disk and
+//! reload them on startup.
+//!
+//! The file is a simple length-prefixed binary format:
+//!
+//! ```text
+//! magic "NDHT" | version u8 | count u32
+//! repeated: key_len u32 | key | value_len u32 | value
+//!           | publisher_len u32 | publisher | ttl_secs u64 (0 = no expiry)
+//! ```
+//!
+//! Integers are big-endian. Expiry is stored as remaining seconds because
+//! record expiry uses a monotonic clock that does not survive a restart.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use libp2p::kad::{Record, RecordKey};
+use libp2p::PeerId;
+
+/// File magic.
+const MAGIC: &[u8; 4] = b"NDHT";
+
+/// Current format version.
+const VERSION: u8 = 1;
+
+/// Upper bound for a single field, to reject corrupt files early.
+const MAX_FIELD_LEN: u32 = 16 * 1024 * 1024;
+
+/// Write records to `path`, replacing any previous snapshot.
+///
+/// Expired records are skipped. The snapshot is written to a temporary file
+/// and renamed into place so a crash never leaves a truncated file.
+pub fn save_records<'a>(
+    path: &Path,
+    records: impl Iterator<Item = &'a Record>,
+) -> io::Result<usize> {
+    let now = Instant::now();
+    let live: Vec<&Record> = records.filter(|r| !r.is_expired(now)).collect();
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("tmp");
+    {
+        let mut w = BufWriter::new(File::create(&tmp_path)?);
+        w.write_all(MAGIC)?;
+        w.write_all(&[VERSION])?;
+        w.write_all(&(live.len() as u32).to_be_bytes())?;
+        for record in &live {
+            write_field(&mut w, record.key.as_ref())?;
+            write_field(&mut w, &record.value)?;
+            let publisher = record.publisher.map(|p| p.to_bytes()).unwrap_or_default();
+            write_field(&mut w, &publisher)?;
+            let ttl = record
+                .expires
+                .map(|e| e.saturating_duration_since(now).as_secs().max(1))
+                .unwrap_or(0);
+            w.write_all(&ttl.to_be_bytes())?;
+        }
+        w.flush()?;
+        w.get_ref().sync_all()?;
+    }
+    std::fs::rename(&tmp_path, path)?;
+    Ok(live.len())
+}
+
+/// Read records from `path`.
+///
+/// Returns an empty list if the file does not exist.
+pub fn load_records(path: &Path) -> io::Result<Vec<Record>> {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut r = BufReader::new(file);
+
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid("not a DHT record file"));
+    }
+    let mut version = [0u8; 1];
+    r.read_exact(&mut version)?;
+    if version[0] != VERSION {
+        return Err(invalid(&format!(
+            "unsupported DHT record file version {}",
+            version[0]
+        )));
+    }
+
+    let count = read_u32(&mut r)?;
+    let now = Instant::now();
+    let mut records = Vec::with_capacity(count.min(4096) as usize);
+    for _ in 0..count {
+        let key = read_field(&mut r)?;
+        let value = read_field(&mut r)?;
+        let publisher = read_field(&mut r)?;
+        let mut ttl = [0u8; 8];
+        r.read_exact(&mut ttl)?;
+        let ttl = u64::from_be_bytes(ttl);
+
+        let mut record = Record::new(RecordKey::from(key), value);
+        if !publisher.is_empty() {
+            record.publisher =
+                Some(PeerId::from_bytes(&publisher).map_err(|e| invalid(&e.to_string()))?);
+        }
+        if ttl > 0 {
+            record.expires = Some(now + Duration::from_secs(ttl));
+        }
+        records.push(record);
+    }
+    Ok(records)
+}
+
+fn write_field(w: &mut impl Write, data: &[u8]) -> io::Result<()> {
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(data)
+}
+
+fn read_u32(r: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_be_bytes(buf))
+}
+
+fn read_field(r: &mut impl Read) -> io::Result<Vec<u8>> {
+    let len = read_u32(r)?;
+    if len > MAX_FIELD_LEN {
+        return Err(invalid("DHT record field too large"));
+    }
+    let mut data = vec![0u8; len as usize];
+    r.read_exact(&mut data)?;
+    Ok(data)
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("dht_records.bin");
+
+        let mut with_expiry = Record::new(RecordKey::new(&b"key-1"), b"value-1".to_vec());
+        with_expiry.publisher = Some(PeerId::random());
+        with_expiry.expires = Some(Instant::now() + Duration::from_secs(3600));
+        let plain = Record::new(RecordKey::new(&b"key-2"), b"value-2".to_vec());
+
+        let saved = save_records(&path, [&with_expiry, &plain].into_iter()).unwrap();
+        assert_eq!(saved, 2);
+
+        let loaded = load_records(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].key, with_expiry.key);
+        assert_eq!(loaded[0].value, with_expiry.value);
+        assert_eq!(loaded[0].publisher, with_expiry.publisher);
+        assert!(loaded[0].expires.is_some());
+        assert_eq!(loaded[1].key, plain.key);
+        assert!(loaded[1].publisher.is_none());
+        assert!(loaded[1].expires.is_none());
+    }
+
+    #[test]
+    fn test_expired_records_skipped() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("dht_records.bin");
+
+        let mut expired = Record::new(RecordKey::new(&b"old"), b"value".to_vec());
+        expired.expires = Some(Instant::now() - Duration::from_secs(1));
+
+        assert_eq!(save_records(&path, [&expired].into_iter()).unwrap(), 0);
+        assert!(load_records(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_load_missing_and_corrupt() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("dht_records.bin");
+        assert!(load_records(&path).unwrap().is_empty());
+
+        std::fs::write(&path, b"garbage").unwrap();
+        let err = load_records(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,451 @@
+//! Bootstrap node discovery from DNS TXT records.
+//!
+//! Bootstrap nodes are published as dnsaddr records, the convention libp2p
+//! uses: TXT records on `_dnsaddr.<domain>`, each holding
+//! `dnsaddr=<multiaddr>` where the multiaddr ends in `/p2p/<peer id>`.
+//! A record may point at another dnsaddr domain (`/dnsaddr/<name>/...`),
+//! which is followed up to [`MAX_DNSADDR_DEPTH`] levels, so operators can
+//! rotate infrastructure by editing DNS instead of shipping new binaries.
+//!
+//! Resolved nodes are cached to a file. A cache younger than its TTL is
+//! used without asking DNS; an older one is still used when the lookup
+//! fails. With neither, the node falls back to its static bootstrap list.
+
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use libp2p::multiaddr::Protocol;
+use libp2p::{Multiaddr, PeerId};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+/// Most nested dnsaddr domains followed from the configured one.
+pub const MAX_DNSADDR_DEPTH: usize = 4;
+
+/// Most bootstrap nodes taken from DNS.
+pub const MAX_DNS_BOOTSTRAP_NODES: usize = 32;
+
+/// Prefix of a dnsaddr TXT record's value.
+const DNSADDR_PREFIX: &str = "dnsaddr=";
+
+/// Configuration for DNS bootstrap discovery.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnsBootstrapConfig {
+    /// Domain whose `_dnsaddr` TXT records list the bootstrap nodes.
+    pub domain: String,
+
+    /// File the resolved nodes are cached in.
+    ///
+    /// Default: None (resolve on every start, no fallback to a cache).
+    pub cache_path: Option<PathBuf>,
+
+    /// How long cached nodes are used without asking DNS.
+    ///
+    /// Default: 24 hours.
+    pub cache_ttl: Duration,
+
+    /// Timeout for the whole lookup, nested domains included.
+    ///
+    /// Default: 5 seconds.
+    pub timeout: Duration,
+}
+
+impl DnsBootstrapConfig {
+    /// Discover bootstrap nodes from `domain`'s dnsaddr records.
+    pub fn new(domain: impl Into<String>) -> Self {
+        Self {
+            domain: domain.into(),
+            cache_path: None,
+            cache_ttl: Duration::from_secs(24 * 60 * 60),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Cache resolved nodes in a file.
+    pub fn with_cache(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cache_path = Some(path.into());
+        self
+    }
+
+    /// Set how long cached nodes are used without asking DNS.
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// Set the lookup timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Looks up TXT records.
+#[async_trait]
+pub trait TxtResolver: Send + Sync {
+    /// The TXT record values of `name`, each record's strings joined.
+    async fn lookup_txt(&self, name: &str) -> io::Result<Vec<String>>;
+}
+
+/// [`TxtResolver`] using the system's DNS configuration.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemTxtResolver;
+
+#[async_trait]
+impl TxtResolver for SystemTxtResolver {
+    async fn lookup_txt(&self, name: &str) -> io::Result<Vec<String>> {
+        let resolver = hickory_resolver::TokioAsyncResolver::tokio_from_system_conf()?;
+        let lookup = resolver
+            .txt_lookup(name)
+            .await
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        Ok(lookup
+            .iter()
+            .map(|txt| {
+                txt.txt_data()
+                    .iter()
+                    .map(|part| String::from_utf8_lossy(part))
+                    .collect()
+            })
+            .collect())
+    }
+}
+
+/// Where discovered bootstrap nodes came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootstrapSource {
+    /// A DNS lookup.
+    Dns,
+    /// The cache, still within its TTL.
+    Cache,
+    /// The cache, past its TTL, because the lookup failed.
+    StaleCache,
+    /// Nothing was found; use the static list.
+    None,
+}
+
+/// Bootstrap nodes discovered through DNS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootstrapDiscovery {
+    /// Where the nodes came from.
+    pub source: BootstrapSource,
+    /// The nodes, with their addresses less the `/p2p` component.
+    pub nodes: Vec<(PeerId, Multiaddr)>,
+}
+
+/// Cached nodes, as stored on disk.
+#[derive(Debug, Serialize, Deserialize)]
+struct BootstrapCache {
+    /// Domain the nodes were resolved from.
+    domain: String,
+    /// When they were resolved, in seconds since the Unix epoch.
+    resolved_at: u64,
+    /// Full multiaddrs, `/p2p` included.
+    nodes: Vec<String>,
+}
+
+/// Split a multiaddr ending in `/p2p/<peer id>` into the peer and the
+/// address to dial.
+pub fn split_peer_addr(addr: &Multiaddr) -> Option<(PeerId, Multiaddr)> {
+    let mut addr = addr.clone();
+    match addr.pop()? {
+        Protocol::P2p(peer) => Some((peer, addr)),
+        _ => None,
+    }
+}
+
+/// Parse dnsaddr TXT values into nodes and nested dnsaddr domains.
+///
+/// Values without the `dnsaddr=` prefix, unparsable multiaddrs and
+/// addresses without a peer ID are skipped.
+pub fn parse_dnsaddr_records(records: &[String]) -> (Vec<(PeerId, Multiaddr)>, Vec<String>) {
+    let mut nodes = Vec::new();
+    let mut nested = Vec::new();
+    for record in records {
+        let Some(value) = record.trim().strip_prefix(DNSADDR_PREFIX) else {
+            continue;
+        };
+        let Ok(addr) = value.parse::<Multiaddr>() else {
+            debug!(record = %record, "Skipping unparsable dnsaddr record");
+            continue;
+        };
+        if let Some(Protocol::Dnsaddr(domain)) = addr.iter().next() {
+            nested.push(domain.to_string());
+        } else if let Some(node) = split_peer_addr(&addr) {
+            nodes.push(node);
+        } else {
+            debug!(record = %record, "Skipping dnsaddr record without a peer ID");
+        }
+    }
+    (nodes, nested)
+}
+
+/// Resolve `domain`'s dnsaddr records, following nested domains.
+///
+/// Fails only if the configured domain itself can't be looked up; nested
+/// domains that fail are skipped.
+pub async fn resolve_dnsaddr(
+    resolver: &dyn TxtResolver,
+    domain: &str,
+) -> io::Result<Vec<(PeerId, Multiaddr)>> {
+    let mut nodes = Vec::new();
+    let mut seen = HashSet::new();
+    let mut pending = vec![(domain.to_string(), 0)];
+    while let Some((domain, depth)) = pending.pop() {
+        if !seen.insert(domain.clone()) {
+            continue;
+        }
+        let records = match resolver.lookup_txt(&format!("_dnsaddr.{}", domain)).await {
+            Ok(records) => records,
+            Err(e) if depth == 0 => return Err(e),
+            Err(e) => {
+                debug!(domain = %domain, error = %e, "Nested dnsaddr lookup failed");
+                continue;
+            }
+        };
+        let (found, nested) = parse_dnsaddr_records(&records);
+        for node in found {
+            if !nodes.contains(&node) {
+                nodes.push(node);
+            }
+        }
+        if depth < MAX_DNSADDR_DEPTH {
+            pending.extend(nested.into_iter().map(|domain| (domain, depth + 1)));
+        }
+    }
+    nodes.truncate(MAX_DNS_BOOTSTRAP_NODES);
+    Ok(nodes)
+}
+
+fn unix_now() -> u64 {
+    nodalync_crypto::current_timestamp() / 1000
+}
+
+/// Read the cache, if it exists and is for `domain`.
+fn load_cache(path: &Path, domain: &str) -> Option<(u64, Vec<(PeerId, Multiaddr)>)> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            warn!(path = %path.display(), error = %e, "Failed to read bootstrap cache");
+            return None;
+        }
+    };
+    let cache: BootstrapCache = match serde_json::from_slice(&bytes) {
+        Ok(cache) => cache,
+        Err(e) => {
+            warn!(path = %path.display(), error = %e, "Ignoring corrupt bootstrap cache");
+            return None;
+        }
+    };
+    if cache.domain != domain {
+        return None;
+    }
+    let nodes = cache
+        .nodes
+        .iter()
+        .filter_map(|addr| addr.parse::<Multiaddr>().ok())
+        .filter_map(|addr| split_peer_addr(&addr))
+        .collect();
+    Some((cache.resolved_at, nodes))
+}
+
+/// Write the cache, replacing it atomically.
+fn save_cache(path: &Path, domain: &str, nodes: &[(PeerId, Multiaddr)]) -> io::Result<()> {
+    let cache = BootstrapCache {
+        domain: domain.to_string(),
+        resolved_at: unix_now(),
+        nodes: nodes
+            .iter()
+            .map(|(peer, addr)| addr.clone().with(Protocol::P2p(*peer)).to_string())
+            .collect(),
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, serde_json::to_vec_pretty(&cache)?)?;
+    std::fs::rename(&tmp_path, path)
+}
+
+/// Discover bootstrap nodes: from a fresh cache, else from DNS (caching
+/// the result), else from a stale cache.
+pub async fn discover_bootstrap_nodes(
+    config: &DnsBootstrapConfig,
+    resolver: &dyn TxtResolver,
+) -> BootstrapDiscovery {
+    let cached = config
+        .cache_path
+        .as_deref()
+        .and_then(|path| load_cache(path, &config.domain))
+        .filter(|(_, nodes)| !nodes.is_empty());
+    if let Some((resolved_at, nodes)) = &cached {
+        if unix_now().saturating_sub(*resolved_at) < config.cache_ttl.as_secs() {
+            return BootstrapDiscovery {
+                source: BootstrapSource::Cache,
+                nodes: nodes.clone(),
+            };
+        }
+    }
+
+    let lookup = tokio::time::timeout(config.timeout, resolve_dnsaddr(resolver, &config.domain))
+        .await
+        .unwrap_or_else(|_| Err(io::Error::new(io::ErrorKind::TimedOut, "lookup timed out")));
+    match lookup {
+        Ok(nodes) if !nodes.is_empty() => {
+            if let Some(path) = &config.cache_path {
+                if let Err(e) = save_cache(path, &config.domain, &nodes) {
+                    warn!(path = %path.display(), error = %e, "Failed to write bootstrap cache");
+                }
+            }
+            return BootstrapDiscovery {
+                source: BootstrapSource::Dns,
+                nodes,
+            };
+        }
+        Ok(_) => warn!(domain = %config.domain, "No bootstrap nodes in dnsaddr records"),
+        Err(e) => warn!(domain = %config.domain, error = %e, "Bootstrap DNS lookup failed"),
+    }
+
+    match cached {
+        Some((_, nodes)) => BootstrapDiscovery {
+            source: BootstrapSource::StaleCache,
+            nodes,
+        },
+        None => BootstrapDiscovery {
+            source: BootstrapSource::None,
+            nodes: Vec::new(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    const PEER_A: &str = "12D3KooWMqrUmZm4e1BJTRMWqKHCe1TSX9Vu83uJLEyCGr2dUjYm";
+    const PEER_B: &str = "12D3KooWQiK8uHf877wena9MAPHHprXkmGRhAmXAYakRsMfdnk7P";
+
+    /// Resolver answering from a fixed table, counting lookups.
+    #[derive(Default)]
+    struct MockResolver {
+        records: HashMap<String, Vec<String>>,
+        lookups: AtomicUsize,
+    }
+
+    impl MockResolver {
+        fn with(mut self, name: &str, records: &[String]) -> Self {
+            self.records.insert(name.to_string(), records.to_vec());
+            self
+        }
+    }
+
+    #[async_trait]
+    impl TxtResolver for MockResolver {
+        async fn lookup_txt(&self, name: &str) -> io::Result<Vec<String>> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            self.records
+                .get(name)
+                .cloned()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, name.to_string()))
+        }
+    }
+
+    fn record(addr: &str) -> String {
+        format!("dnsaddr={}", addr)
+    }
+
+    fn resolver() -> MockResolver {
+        MockResolver::default()
+            .with(
+                "_dnsaddr.bootstrap.example",
+                &[
+                    record(&format!("/ip4/10.0.0.1/tcp/9000/p2p/{}", PEER_A)),
+                    record("/dnsaddr/eu.bootstrap.example"),
+                    "v=spf1 -all".to_string(),
+                    record("/ip4/10.0.0.9/tcp/9000"),
+                ],
+            )
+            .with(
+                "_dnsaddr.eu.bootstrap.example",
+                &[
+                    record(&format!("/dns4/eu.example/tcp/9000/p2p/{}", PEER_B)),
+                    // Loops back: followed once
+                    record("/dnsaddr/bootstrap.example"),
+                ],
+            )
+    }
+
+    #[test]
+    fn test_parse_dnsaddr_records() {
+        let (nodes, nested) = parse_dnsaddr_records(&[
+            record(&format!("/ip4/10.0.0.1/tcp/9000/p2p/{}", PEER_A)),
+            record("/dnsaddr/eu.bootstrap.example"),
+            record("not a multiaddr"),
+            record("/ip4/10.0.0.9/tcp/9000"),
+            "unrelated".to_string(),
+        ]);
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].0, PEER_A.parse::<PeerId>().unwrap());
+        assert_eq!(nodes[0].1, "/ip4/10.0.0.1/tcp/9000".parse().unwrap());
+        assert_eq!(nested, vec!["eu.bootstrap.example".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_nested_dnsaddr() {
+        let nodes = resolve_dnsaddr(&resolver(), "bootstrap.example")
+            .await
+            .unwrap();
+        let peers: Vec<String> = nodes.iter().map(|(peer, _)| peer.to_string()).collect();
+        assert_eq!(peers, vec![PEER_A.to_string(), PEER_B.to_string()]);
+
+        assert!(resolve_dnsaddr(&resolver(), "missing.example")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_discover_caches_and_falls_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let config =
+            DnsBootstrapConfig::new("bootstrap.example").with_cache(temp_dir.path().join("b.json"));
+
+        // Resolved from DNS and cached
+        let resolver = resolver();
+        let discovery = discover_bootstrap_nodes(&config, &resolver).await;
+        assert_eq!(discovery.source, BootstrapSource::Dns);
+        assert_eq!(discovery.nodes.len(), 2);
+
+        // A fresh cache answers without a lookup
+        let lookups = resolver.lookups.load(Ordering::SeqCst);
+        let cached = discover_bootstrap_nodes(&config, &resolver).await;
+        assert_eq!(cached.source, BootstrapSource::Cache);
+        assert_eq!(cached.nodes, discovery.nodes);
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), lookups);
+
+        // Past its TTL, the cache is refreshed; with DNS down it's still used
+        let expired = config.clone().with_cache_ttl(Duration::ZERO);
+        let down = MockResolver::default();
+        let stale = discover_bootstrap_nodes(&expired, &down).await;
+        assert_eq!(stale.source, BootstrapSource::StaleCache);
+        assert_eq!(stale.nodes, discovery.nodes);
+
+        // A cache for another domain doesn't count
+        let other =
+            DnsBootstrapConfig::new("other.example").with_cache(temp_dir.path().join("b.json"));
+        let none = discover_bootstrap_nodes(&other, &down).await;
+        assert_eq!(none.source, BootstrapSource::None);
+        assert!(none.nodes.is_empty());
+
+        // A corrupt cache is ignored
+        std::fs::write(temp_dir.path().join("b.json"), b"{").unwrap();
+        let none = discover_bootstrap_nodes(&expired, &down).await;
+        assert_eq!(none.source, BootstrapSource::None);
+    }
+}
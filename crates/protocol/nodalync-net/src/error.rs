@@ -2,6 +2,7 @@
 //!
 //! This module defines all error types for the nodalync-net crate.
 
+use nodalync_types::ErrorCode;
 use thiserror::Error;
 
 /// Network-specific errors.
@@ -90,16 +91,76 @@ pub enum NetworkError {
         libp2p_peer_id: Option<String>,
     },
 
+    /// The server requires a signed answer to its challenge before serving
+    /// the query.
+    #[error("query challenge required")]
+    ChallengeRequired {
+        /// The challenge to sign and retry with.
+        challenge: nodalync_wire::QueryChallenge,
+    },
+
     /// Query error returned by server.
-    #[error("query error: {code:?} - {message}")]
+    #[error("query error: {code} - {message}")]
     QueryError {
         /// Error code from server.
-        code: nodalync_types::ErrorCode,
+        code: ErrorCode,
         /// Error message from server.
         message: String,
+        /// How long the server asked us to wait before retrying, in
+        /// milliseconds, for temporary errors.
+        retry_after_ms: Option<u64>,
+        /// Other providers the server suggested, as base58 libp2p peer IDs.
+        alternative_providers: Vec<String>,
     },
 }
 
+impl From<nodalync_wire::QueryErrorPayload> for NetworkError {
+    fn from(error: nodalync_wire::QueryErrorPayload) -> Self {
+        Self::QueryError {
+            code: error.error_code,
+            message: error.message.unwrap_or_else(|| "Unknown error".to_string()),
+            retry_after_ms: error.retry_after_ms,
+            alternative_providers: error.alternative_providers,
+        }
+    }
+}
+
+impl NetworkError {
+    /// Get the protocol error code for this error.
+    ///
+    /// A server's QUERY_ERROR keeps the code it was sent with.
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            Self::QueryError { code, .. } => *code,
+            Self::ChannelRequired { .. } => ErrorCode::ChannelNotFound,
+            Self::ChallengeRequired { .. } => ErrorCode::ChallengeRequired,
+            Self::Timeout(_) => ErrorCode::Timeout,
+            Self::PeerNotFound(_) | Self::PeerIdMappingNotFound(_) => ErrorCode::PeerNotFound,
+            Self::RecordNotFound => ErrorCode::NotFound,
+            Self::Transport(_)
+            | Self::ConnectionFailed(_)
+            | Self::DhtError(_)
+            | Self::MaxRetriesExceeded { .. }
+            | Self::GossipSubError(_)
+            | Self::BootstrapFailed(_)
+            | Self::DialError(_)
+            | Self::Io(_) => ErrorCode::ConnectionFailed,
+            Self::Encoding(_)
+            | Self::Decoding(_)
+            | Self::InvalidResponseType { .. }
+            | Self::ChannelClosed
+            | Self::SwarmNotRunning
+            | Self::AlreadyListening(_) => ErrorCode::InternalError,
+        }
+    }
+}
+
+impl From<&NetworkError> for ErrorCode {
+    fn from(error: &NetworkError) -> Self {
+        error.error_code()
+    }
+}
+
 /// Result type alias using NetworkError.
 pub type NetworkResult<T> = Result<T, NetworkError>;
 
@@ -119,6 +180,28 @@ mod tests {
         assert_eq!(format!("{}", err), "max retries exceeded after 3 attempts");
     }
 
+    #[test]
+    fn test_error_code_mapping() {
+        assert_eq!(
+            NetworkError::Timeout("30s elapsed".to_string()).error_code(),
+            ErrorCode::Timeout
+        );
+        assert_eq!(
+            ErrorCode::from(&NetworkError::DialError("refused".to_string())),
+            ErrorCode::ConnectionFailed
+        );
+
+        // A server's code passes through unchanged
+        let err = NetworkError::QueryError {
+            code: ErrorCode::RateLimited,
+            message: "busy".to_string(),
+            retry_after_ms: Some(100),
+            alternative_providers: Vec::new(),
+        };
+        assert_eq!(err.error_code(), ErrorCode::RateLimited);
+        assert_eq!(err.to_string(), "query error: RATE_LIMITED - busy");
+    }
+
     #[test]
     fn test_error_debug() {
         let err = NetworkError::RecordNotFound;
@@ -10,7 +10,7 @@ use nodalync_wire::Message;
 ///
 /// These events are returned by `Network::next_event()` and represent
 /// significant occurrences in the P2P network.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[non_exhaustive]
 pub enum NetworkEvent {
     /// A message was received from a peer.
@@ -7,6 +7,12 @@
 //! - **Request-Response**: Point-to-point messaging for queries and channels
 //! - **GossipSub**: Broadcast messaging for announcements
 //! - **Peer Management**: Connection handling and peer discovery
+//! - **Infrastructure Nodes**: Per-IP rate limiting, DHT record persistence,
+//!   and connection statistics for public bootstrap/relay nodes
+//! - **Replay Log**: Opt-in record of every wire message sent and received,
+//!   for reproducing bugs
+//! - **Session Capture**: Opt-in compact binary record of decoded messages
+//!   per peer, for traffic, error and payment analysis
 //!
 //! # Overview
 //!
@@ -102,19 +108,37 @@
 //! ```
 
 pub mod behaviour;
+pub mod capture;
 pub mod codec;
 pub mod config;
+pub mod dht_persist;
+pub mod dns_bootstrap;
 pub mod error;
 pub mod event;
 pub mod node;
 pub mod peer_id;
+pub mod rate_limit;
+pub mod replay;
+pub mod stats;
 pub mod traits;
 pub mod transport;
 
 // Re-export main types at crate root
 
+// Session capture
+pub use capture::{
+    read_capture, CaptureConfig, CaptureRecord, CaptureSession, PaymentFlow, PeerSummary,
+    SessionRecorder, SessionSummary, TrafficCount,
+};
+
 // Configuration
-pub use config::NetworkConfig;
+pub use config::{NetworkConfig, RelayConfig, MAX_RELAY_HOPS};
+
+// DNS bootstrap discovery
+pub use dns_bootstrap::{
+    discover_bootstrap_nodes, split_peer_addr, BootstrapDiscovery, BootstrapSource,
+    DnsBootstrapConfig, SystemTxtResolver, TxtResolver,
+};
 
 // Error types
 pub use error::{NetworkError, NetworkResult};
@@ -128,6 +152,15 @@ pub use node::NetworkNode;
 // Peer ID mapping
 pub use peer_id::PeerIdMapper;
 
+// Rate limiting and connection statistics
+pub use rate_limit::{IpRateLimiter, RateLimitConfig};
+pub use stats::ConnectionStats;
+
+// Replay log
+pub use replay::{
+    read_replay_log, ReplayDirection, ReplayEntry, ReplayKind, ReplayLog, ReplayLogConfig,
+};
+
 // The Network trait
 pub use traits::Network;
 
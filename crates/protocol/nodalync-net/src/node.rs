@@ -4,11 +4,17 @@
 //! the concrete implementation of the `Network` trait.
 
 use crate::behaviour::{NodalyncBehaviour, NodalyncBehaviourEvent};
+use crate::capture::{CaptureRecord, SessionRecorder};
 use crate::codec::{NodalyncRequest, NodalyncResponse};
-use crate::config::NetworkConfig;
+use crate::config::{NetworkConfig, RelayConfig};
+use crate::dht_persist;
+use crate::dns_bootstrap::{discover_bootstrap_nodes, SystemTxtResolver};
 use crate::error::{NetworkError, NetworkResult};
 use crate::event::NetworkEvent;
 use crate::peer_id::PeerIdMapper;
+use crate::rate_limit::{ip_of, IpRateLimiter};
+use crate::replay::{ReplayDirection, ReplayEntry, ReplayKind, ReplayLog};
+use crate::stats::{ConnectionCounters, ConnectionStats};
 use crate::traits::Network;
 use crate::transport::build_transport;
 
@@ -16,22 +22,35 @@ use async_trait::async_trait;
 use futures::StreamExt;
 use libp2p::{
     gossipsub::IdentTopic,
-    kad::{self, QueryResult, RecordKey},
+    kad::{self, store::RecordStore, QueryResult, RecordKey},
     request_response::{self, OutboundRequestId, ResponseChannel},
-    swarm::{dial_opts::DialOpts, SwarmEvent},
+    swarm::{dial_opts::DialOpts, ConnectionId, SwarmEvent},
     Multiaddr, PeerId, Swarm,
 };
 use nodalync_crypto::{
-    generate_identity, peer_id_from_public_key, Hash, PeerId as NodalyncPeerId, PrivateKey,
+    current_timestamp, generate_identity, peer_id_from_public_key, Hash, PeerId as NodalyncPeerId,
+    PrivateKey,
 };
 use nodalync_wire::{
-    create_message, decode_message, decode_payload, encode_message, encode_payload,
-    AnnouncePayload, ChannelClosePayload, ChannelOpenPayload, Message, MessageType,
-    PreviewRequestPayload, PreviewResponsePayload, QueryErrorPayload, QueryRequestPayload,
-    QueryResponsePayload, SearchPayload, SearchResponsePayload, SettleConfirmPayload,
+    create_message, decode_message, decode_payload, encode_message, encode_message_owned,
+    encode_payload, storefront_key, AnnouncePayload, AvailabilityCheckPayload,
+    AvailabilityResultPayload, ChannelClosePayload, ChannelOpenPayload, ChannelSyncPayload,
+    ChannelSyncResponsePayload, ChunkRequestPayload, ChunkResponsePayload, FraudProofPayload,
+    GroupRequestPayload, GroupResponsePayload, InvoiceAckPayload, InvoicePayPayload,
+    InvoicePayload, InvoiceRequestPayload, Message, MessageType, PeerInfoPayload, PingPayload,
+    PongPayload, PreviewRequestPayload, PreviewResponsePayload, QueryErrorPayload,
+    QueryRequestPayload, QueryResponsePayload, RelayQueryPayload, RelayResponsePayload,
+    ReportPayload, RevocationPayload, SearchPayload, SearchResponsePayload, SettleConfirmPayload,
+    SnapshotRequestPayload, SnapshotResponsePayload, Storefront, SyncPullPayload,
+    SyncPullResponsePayload, SyncPushAckPayload, SyncPushPayload, TombstonePayload,
+    UsageReportAckPayload, UsageReportPayload, VersionRequestPayload, VersionResponsePayload,
 };
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock as StdRwLock};
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex, RwLock as StdRwLock};
+use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, oneshot, Mutex, RwLock};
 use tracing::{debug, info, warn};
 
@@ -44,8 +63,17 @@ struct SwarmContext {
     connected_peers: Arc<StdRwLock<std::collections::HashSet<PeerId>>>,
     listen_addrs: Arc<StdRwLock<Vec<Multiaddr>>>,
     gossip_topic: String,
+    counters: Arc<ConnectionCounters>,
+    rate_limiter: Option<IpRateLimiter>,
+    dht_record_path: Option<PathBuf>,
+    serve_requests: bool,
+    /// Set while the ops layer can't keep up with incoming announcements.
+    broadcast_backpressure: Arc<AtomicBool>,
 }
 
+/// How often the swarm task prunes rate-limit state and persists DHT records.
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Commands sent to the swarm task.
 #[allow(dead_code)]
 enum SwarmCommand {
@@ -123,6 +151,11 @@ enum SwarmCommand {
         request_id: libp2p::request_response::InboundRequestId,
         data: Vec<u8>,
     },
+
+    /// Write DHT records to the configured persistence file.
+    PersistDhtRecords {
+        response: oneshot::Sender<NetworkResult<usize>>,
+    },
 }
 
 /// Type alias for pending request map to reduce type complexity.
@@ -162,9 +195,28 @@ pub struct NetworkNode {
     #[allow(dead_code)]
     pending_requests: PendingRequests,
 
+    /// Connection statistics (updated by the swarm task).
+    counters: Arc<ConnectionCounters>,
+
     /// Network configuration.
     config: NetworkConfig,
 
+    /// Correction applied to outgoing message timestamps, in milliseconds.
+    clock_offset: AtomicI64,
+
+    /// Whether incoming announcements are being shed.
+    broadcast_backpressure: Arc<AtomicBool>,
+
+    /// Record of wire messages sent and received, if enabled.
+    replay_log: Option<StdMutex<ReplayLog>>,
+
+    /// Capture of decoded messages sent and received, if enabled.
+    capture: Option<StdMutex<SessionRecorder>>,
+
+    /// Requesters of inbound requests not yet answered, so captured
+    /// responses can be attributed to them. Only kept while capturing.
+    inbound_peers: StdMutex<HashMap<libp2p::request_response::InboundRequestId, PeerId>>,
+
     /// GossipSub topic for announcements.
     #[allow(dead_code)]
     announce_topic: IdentTopic,
@@ -203,6 +255,20 @@ impl NetworkNode {
             })?;
         }
 
+        let replay_log = config
+            .replay_log
+            .as_ref()
+            .map(|replay| ReplayLog::open(replay).map(StdMutex::new))
+            .transpose()?;
+        let capture = config
+            .capture
+            .as_ref()
+            .map(|capture| {
+                SessionRecorder::open(capture, local_peer_id, current_timestamp())
+                    .map(StdMutex::new)
+            })
+            .transpose()?;
+
         // Create channels
         let (command_tx, command_rx) = mpsc::channel(256);
         let (event_tx, event_rx) = mpsc::channel(256);
@@ -221,6 +287,8 @@ impl NetworkNode {
         let connected_peers_clone = connected_peers_set.clone();
         let listen_addrs = Arc::new(StdRwLock::new(Vec::new()));
         let listen_addrs_clone = listen_addrs.clone();
+        let counters = Arc::new(ConnectionCounters::default());
+        let broadcast_backpressure = Arc::new(AtomicBool::new(false));
 
         // Subscribe to the announcement topic
         let announce_topic = IdentTopic::new(&config.gossipsub_topic);
@@ -232,6 +300,11 @@ impl NetworkNode {
             connected_peers: connected_peers_clone,
             listen_addrs: listen_addrs_clone,
             gossip_topic,
+            counters: counters.clone(),
+            rate_limiter: config.rate_limit.clone().map(IpRateLimiter::new),
+            dht_record_path: config.dht_record_path.clone(),
+            serve_requests: config.serve_requests,
+            broadcast_backpressure: Arc::clone(&broadcast_backpressure),
         };
         tokio::spawn(async move {
             run_swarm(swarm, command_rx, event_tx, swarm_ctx).await;
@@ -247,7 +320,13 @@ impl NetworkNode {
             command_tx,
             event_rx: Arc::new(Mutex::new(event_rx)),
             pending_requests,
+            counters,
             config,
+            clock_offset: AtomicI64::new(0),
+            broadcast_backpressure,
+            replay_log,
+            capture,
+            inbound_peers: StdMutex::new(HashMap::new()),
             announce_topic,
         })
     }
@@ -282,6 +361,20 @@ impl NetworkNode {
             })?;
         }
 
+        let replay_log = config
+            .replay_log
+            .as_ref()
+            .map(|replay| ReplayLog::open(replay).map(StdMutex::new))
+            .transpose()?;
+        let capture = config
+            .capture
+            .as_ref()
+            .map(|capture| {
+                SessionRecorder::open(capture, local_peer_id, current_timestamp())
+                    .map(StdMutex::new)
+            })
+            .transpose()?;
+
         // Create channels
         let (command_tx, command_rx) = mpsc::channel(256);
         let (event_tx, event_rx) = mpsc::channel(256);
@@ -300,6 +393,8 @@ impl NetworkNode {
         let connected_peers_clone = connected_peers_set.clone();
         let listen_addrs = Arc::new(StdRwLock::new(Vec::new()));
         let listen_addrs_clone = listen_addrs.clone();
+        let counters = Arc::new(ConnectionCounters::default());
+        let broadcast_backpressure = Arc::new(AtomicBool::new(false));
 
         let announce_topic = IdentTopic::new(&config.gossipsub_topic);
 
@@ -310,6 +405,11 @@ impl NetworkNode {
             connected_peers: connected_peers_clone,
             listen_addrs: listen_addrs_clone,
             gossip_topic,
+            counters: counters.clone(),
+            rate_limiter: config.rate_limit.clone().map(IpRateLimiter::new),
+            dht_record_path: config.dht_record_path.clone(),
+            serve_requests: config.serve_requests,
+            broadcast_backpressure: Arc::clone(&broadcast_backpressure),
         };
         tokio::spawn(async move {
             run_swarm(swarm, command_rx, event_tx, swarm_ctx).await;
@@ -325,27 +425,54 @@ impl NetworkNode {
             command_tx,
             event_rx: Arc::new(Mutex::new(event_rx)),
             pending_requests,
+            counters,
             config,
+            clock_offset: AtomicI64::new(0),
+            broadcast_backpressure,
+            replay_log,
+            capture,
+            inbound_peers: StdMutex::new(HashMap::new()),
             announce_topic,
         })
     }
 
+    /// Bootstrap nodes to dial: those discovered through DNS when
+    /// configured and found, else the static list.
+    async fn bootstrap_nodes(&self) -> Vec<(PeerId, Multiaddr)> {
+        if let Some(dns) = &self.config.dns_bootstrap {
+            let discovery = discover_bootstrap_nodes(dns, &SystemTxtResolver).await;
+            if !discovery.nodes.is_empty() {
+                tracing::info!(
+                    "Discovered {} bootstrap node(s) from {} ({:?})",
+                    discovery.nodes.len(),
+                    dns.domain,
+                    discovery.source
+                );
+                return discovery.nodes;
+            }
+            tracing::warn!(
+                "No bootstrap nodes discovered from {}, using the static list",
+                dns.domain
+            );
+        }
+        self.config.bootstrap_nodes.clone()
+    }
+
     /// Bootstrap the node by connecting to bootstrap peers.
     /// If no bootstrap nodes are configured, this succeeds immediately (first node in network).
     pub async fn bootstrap(&self) -> NetworkResult<()> {
+        let bootstrap_nodes = self.bootstrap_nodes().await;
+
         // If no bootstrap nodes, we're the first node - nothing to do
-        if self.config.bootstrap_nodes.is_empty() {
+        if bootstrap_nodes.is_empty() {
             tracing::info!("No bootstrap nodes configured - starting as first node in network");
             return Ok(());
         }
 
-        tracing::info!(
-            "Bootstrapping with {} node(s)",
-            self.config.bootstrap_nodes.len()
-        );
+        tracing::info!("Bootstrapping with {} node(s)", bootstrap_nodes.len());
 
         // Add bootstrap nodes to the routing table AND dial them
-        for (peer_id, addr) in &self.config.bootstrap_nodes {
+        for (peer_id, addr) in &bootstrap_nodes {
             tracing::info!("Adding bootstrap node {} at {}", peer_id, addr);
 
             // Add address to Kademlia routing table
@@ -419,6 +546,47 @@ impl NetworkNode {
         rx.await.map_err(|_| NetworkError::ChannelClosed)?
     }
 
+    /// Subscribe to announcements of content filed under a tag.
+    ///
+    /// Publishers also announce on the topic of each ancestor tag, so
+    /// subscribing to `science` receives `science/biology` announcements.
+    pub async fn subscribe_tag(&self, tag: &str) -> NetworkResult<()> {
+        let topic = self
+            .config
+            .tag_topic(tag)
+            .ok_or_else(|| NetworkError::GossipSubError(format!("invalid tag: {:?}", tag)))?;
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send(SwarmCommand::GossipSubscribe {
+                topic,
+                response: tx,
+            })
+            .await
+            .map_err(|_| NetworkError::ChannelClosed)?;
+
+        rx.await.map_err(|_| NetworkError::ChannelClosed)?
+    }
+
+    /// Get a snapshot of connection statistics.
+    pub fn connection_stats(&self) -> ConnectionStats {
+        self.counters.snapshot()
+    }
+
+    /// Write the DHT record store to the configured persistence file.
+    ///
+    /// Records are also persisted periodically while they change; call this
+    /// before shutting down so the latest records survive a restart.
+    /// Returns the number of records written (0 if persistence is disabled).
+    pub async fn persist_dht_records(&self) -> NetworkResult<usize> {
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send(SwarmCommand::PersistDhtRecords { response: tx })
+            .await
+            .map_err(|_| NetworkError::ChannelClosed)?;
+
+        rx.await.map_err(|_| NetworkError::ChannelClosed)?
+    }
+
     /// Send a request with retry logic.
     async fn send_with_retry(&self, peer: PeerId, data: Vec<u8>) -> NetworkResult<Vec<u8>> {
         let mut last_error = None;
@@ -459,10 +627,8 @@ impl NetworkNode {
 
     /// Create a signed message.
     fn create_signed_message(&self, message_type: MessageType, payload: Vec<u8>) -> Message {
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
+        let timestamp =
+            current_timestamp().saturating_add_signed(self.clock_offset.load(Ordering::Relaxed));
 
         create_message(
             message_type,
@@ -472,6 +638,50 @@ impl NetworkNode {
             &self.private_key,
         )
     }
+
+    /// Add a wire message to the replay log and the session capture, if
+    /// enabled.
+    ///
+    /// `build` turns the bare entry into the full one (peer, topic).
+    fn record_replay(
+        &self,
+        direction: ReplayDirection,
+        kind: ReplayKind,
+        data: &[u8],
+        build: impl FnOnce(ReplayEntry) -> ReplayEntry,
+    ) {
+        if self.replay_log.is_none() && self.capture.is_none() {
+            return;
+        }
+        let now = current_timestamp();
+        let entry = build(
+            ReplayEntry::new(direction, kind, data, now)
+                .with_clock_offset(self.clock_offset.load(Ordering::Relaxed)),
+        );
+
+        if let Some(capture) = &self.capture {
+            let mut record = CaptureRecord::new(direction, kind, data, now);
+            if let Some(peer) = entry.peer_id() {
+                record = record.with_peer(peer);
+            }
+            if let Ok(mut capture) = capture.lock() {
+                if let Err(e) = capture.record(&record) {
+                    warn!("Failed to write session capture: {}", e);
+                }
+            }
+        }
+
+        let Some(log) = &self.replay_log else {
+            return;
+        };
+        let result = match log.lock() {
+            Ok(mut log) => log.record(entry),
+            Err(_) => return,
+        };
+        if let Err(e) = result {
+            warn!("Failed to write replay log: {}", e);
+        }
+    }
 }
 
 #[async_trait]
@@ -526,9 +736,58 @@ impl Network for NetworkNode {
         rx.await.map_err(|_| NetworkError::ChannelClosed)?
     }
 
+    async fn dht_put_storefront(&self, storefront: Storefront) -> NetworkResult<()> {
+        let key = storefront_key(&storefront.publisher).0.to_vec();
+        let value =
+            encode_payload(&storefront).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send(SwarmCommand::DhtPut {
+                key,
+                value,
+                response: tx,
+            })
+            .await
+            .map_err(|_| NetworkError::ChannelClosed)?;
+
+        rx.await.map_err(|_| NetworkError::ChannelClosed)?
+    }
+
+    async fn dht_get_storefront(
+        &self,
+        publisher: &NodalyncPeerId,
+    ) -> NetworkResult<Option<Storefront>> {
+        let key = storefront_key(publisher).0.to_vec();
+
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send(SwarmCommand::DhtGet { key, response: tx })
+            .await
+            .map_err(|_| NetworkError::ChannelClosed)?;
+
+        match rx.await.map_err(|_| NetworkError::ChannelClosed)?? {
+            Some(data) => {
+                let storefront: Storefront =
+                    decode_payload(&data).map_err(|e| NetworkError::Decoding(e.to_string()))?;
+                Ok(Some(storefront))
+            }
+            None => Ok(None),
+        }
+    }
+
     async fn send(&self, peer: PeerId, message: Message) -> NetworkResult<Message> {
         let data = encode_message(&message).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        self.record_replay(ReplayDirection::Outbound, ReplayKind::Request, &data, |e| {
+            e.with_peer(peer)
+        });
         let response_data = self.send_with_retry(peer, data).await?;
+        self.record_replay(
+            ReplayDirection::Inbound,
+            ReplayKind::Response,
+            &response_data,
+            |e| e.with_peer(peer),
+        );
         let response =
             decode_message(&response_data).map_err(|e| NetworkError::Decoding(e.to_string()))?;
         Ok(response)
@@ -536,6 +795,12 @@ impl Network for NetworkNode {
 
     async fn broadcast(&self, message: Message) -> NetworkResult<()> {
         let data = encode_message(&message).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        self.record_replay(
+            ReplayDirection::Outbound,
+            ReplayKind::Broadcast,
+            &data,
+            |e| e.with_topic(&self.config.gossipsub_topic),
+        );
 
         let (tx, rx) = oneshot::channel();
         self.command_tx
@@ -588,7 +853,7 @@ impl Network for NetworkNode {
             }
             MessageType::QueryError => {
                 // Parse the error payload and return appropriate error
-                let error_payload: QueryErrorPayload = decode_payload(&response.payload)
+                let mut error_payload: QueryErrorPayload = decode_payload(&response.payload)
                     .map_err(|e| NetworkError::Decoding(e.to_string()))?;
 
                 // Check if this is a ChannelRequired error with peer info
@@ -602,13 +867,15 @@ impl Network for NetworkNode {
                     });
                 }
 
+                // Challenge issued: the client signs it and retries
+                if let (nodalync_types::ErrorCode::ChallengeRequired, Some(challenge)) =
+                    (error_payload.error_code, error_payload.challenge.take())
+                {
+                    return Err(NetworkError::ChallengeRequired { challenge });
+                }
+
                 // Return generic query error
-                Err(NetworkError::QueryError {
-                    code: error_payload.error_code,
-                    message: error_payload
-                        .message
-                        .unwrap_or_else(|| "Unknown error".to_string()),
-                })
+                Err(error_payload.into())
             }
             _ => Err(NetworkError::InvalidResponseType {
                 expected: "QueryResponse or QueryError".to_string(),
@@ -638,6 +905,27 @@ impl Network for NetworkNode {
         decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))
     }
 
+    async fn send_version_request(
+        &self,
+        peer: PeerId,
+        request: VersionRequestPayload,
+    ) -> NetworkResult<VersionResponsePayload> {
+        let payload =
+            encode_payload(&request).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::VersionRequest, payload);
+
+        let response = self.send(peer, message).await?;
+
+        if response.message_type != MessageType::VersionResponse {
+            return Err(NetworkError::InvalidResponseType {
+                expected: "VersionResponse".to_string(),
+                got: format!("{:?}", response.message_type),
+            });
+        }
+
+        decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))
+    }
+
     async fn send_channel_open(
         &self,
         peer: PeerId,
@@ -660,6 +948,299 @@ impl Network for NetworkNode {
         self.send(peer, message).await
     }
 
+    async fn send_channel_sync(
+        &self,
+        peer: PeerId,
+        payload: ChannelSyncPayload,
+    ) -> NetworkResult<ChannelSyncResponsePayload> {
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::ChannelSync, payload_bytes);
+
+        let response = self.send(peer, message).await?;
+
+        if response.message_type != MessageType::ChannelSyncResponse {
+            return Err(NetworkError::InvalidResponseType {
+                expected: "ChannelSyncResponse".to_string(),
+                got: format!("{:?}", response.message_type),
+            });
+        }
+
+        decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))
+    }
+
+    async fn send_invoice(
+        &self,
+        peer: PeerId,
+        payload: InvoicePayload,
+    ) -> NetworkResult<InvoiceAckPayload> {
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::Invoice, payload_bytes);
+
+        let response = self.send(peer, message).await?;
+
+        if response.message_type != MessageType::InvoiceAck {
+            return Err(NetworkError::InvalidResponseType {
+                expected: "InvoiceAck".to_string(),
+                got: format!("{:?}", response.message_type),
+            });
+        }
+
+        decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))
+    }
+
+    async fn request_invoice(
+        &self,
+        peer: PeerId,
+        payload: InvoiceRequestPayload,
+    ) -> NetworkResult<Message> {
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::InvoiceRequest, payload_bytes);
+        self.send(peer, message).await
+    }
+
+    async fn send_invoice_payment(
+        &self,
+        peer: PeerId,
+        payload: InvoicePayPayload,
+    ) -> NetworkResult<InvoiceAckPayload> {
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::InvoicePay, payload_bytes);
+
+        let response = self.send(peer, message).await?;
+
+        if response.message_type != MessageType::InvoiceAck {
+            return Err(NetworkError::InvalidResponseType {
+                expected: "InvoiceAck".to_string(),
+                got: format!("{:?}", response.message_type),
+            });
+        }
+
+        decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))
+    }
+
+    async fn send_usage_report(
+        &self,
+        peer: PeerId,
+        payload: UsageReportPayload,
+    ) -> NetworkResult<UsageReportAckPayload> {
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::UsageReport, payload_bytes);
+
+        let response = self.send(peer, message).await?;
+
+        if response.message_type != MessageType::UsageReportAck {
+            return Err(NetworkError::InvalidResponseType {
+                expected: "UsageReportAck".to_string(),
+                got: format!("{:?}", response.message_type),
+            });
+        }
+
+        decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))
+    }
+
+    async fn send_relay_query(
+        &self,
+        peer: PeerId,
+        payload: RelayQueryPayload,
+    ) -> NetworkResult<RelayResponsePayload> {
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::RelayQuery, payload_bytes);
+
+        let response = self.send(peer, message).await?;
+
+        match response.message_type {
+            MessageType::RelayResponse => {
+                decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))
+            }
+            MessageType::QueryError => {
+                // A relay on the route, or the provider, refused the query
+                let error_payload: QueryErrorPayload = decode_payload(&response.payload)
+                    .map_err(|e| NetworkError::Decoding(e.to_string()))?;
+                Err(error_payload.into())
+            }
+            _ => Err(NetworkError::InvalidResponseType {
+                expected: "RelayResponse or QueryError".to_string(),
+                got: format!("{:?}", response.message_type),
+            }),
+        }
+    }
+
+    async fn request_chunk(
+        &self,
+        peer: PeerId,
+        payload: ChunkRequestPayload,
+    ) -> NetworkResult<ChunkResponsePayload> {
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::ChunkRequest, payload_bytes);
+
+        let response = self.send(peer, message).await?;
+
+        match response.message_type {
+            MessageType::ChunkResponse => {
+                decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))
+            }
+            MessageType::QueryError => {
+                let error_payload: QueryErrorPayload = decode_payload(&response.payload)
+                    .map_err(|e| NetworkError::Decoding(e.to_string()))?;
+                Err(error_payload.into())
+            }
+            _ => Err(NetworkError::InvalidResponseType {
+                expected: "ChunkResponse or QueryError".to_string(),
+                got: format!("{:?}", response.message_type),
+            }),
+        }
+    }
+
+    async fn send_ping(&self, peer: PeerId, payload: PingPayload) -> NetworkResult<PongPayload> {
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::Ping, payload_bytes);
+
+        let response = self.send(peer, message).await?;
+
+        if response.message_type != MessageType::Pong {
+            return Err(NetworkError::InvalidResponseType {
+                expected: "Pong".to_string(),
+                got: format!("{:?}", response.message_type),
+            });
+        }
+
+        decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))
+    }
+
+    async fn send_peer_info(
+        &self,
+        peer: PeerId,
+        payload: PeerInfoPayload,
+    ) -> NetworkResult<PeerInfoPayload> {
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::PeerInfo, payload_bytes);
+
+        let response = self.send(peer, message).await?;
+
+        if response.message_type != MessageType::PeerInfo {
+            return Err(NetworkError::InvalidResponseType {
+                expected: "PeerInfo".to_string(),
+                got: format!("{:?}", response.message_type),
+            });
+        }
+
+        decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))
+    }
+
+    async fn request_group(
+        &self,
+        peer: PeerId,
+        payload: GroupRequestPayload,
+    ) -> NetworkResult<GroupResponsePayload> {
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::GroupRequest, payload_bytes);
+
+        let response = self.send(peer, message).await?;
+
+        if response.message_type != MessageType::GroupResponse {
+            return Err(NetworkError::InvalidResponseType {
+                expected: "GroupResponse".to_string(),
+                got: format!("{:?}", response.message_type),
+            });
+        }
+
+        decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))
+    }
+
+    async fn request_snapshot(
+        &self,
+        peer: PeerId,
+        payload: SnapshotRequestPayload,
+    ) -> NetworkResult<SnapshotResponsePayload> {
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::SnapshotRequest, payload_bytes);
+
+        let response = self.send(peer, message).await?;
+
+        if response.message_type != MessageType::SnapshotResponse {
+            return Err(NetworkError::InvalidResponseType {
+                expected: "SnapshotResponse".to_string(),
+                got: format!("{:?}", response.message_type),
+            });
+        }
+
+        decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))
+    }
+
+    async fn push_sync_bundle(
+        &self,
+        peer: PeerId,
+        payload: SyncPushPayload,
+    ) -> NetworkResult<SyncPushAckPayload> {
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::SyncPush, payload_bytes);
+
+        let response = self.send(peer, message).await?;
+
+        if response.message_type != MessageType::SyncPushAck {
+            return Err(NetworkError::InvalidResponseType {
+                expected: "SyncPushAck".to_string(),
+                got: format!("{:?}", response.message_type),
+            });
+        }
+
+        decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))
+    }
+
+    async fn pull_sync_bundle(
+        &self,
+        peer: PeerId,
+        payload: SyncPullPayload,
+    ) -> NetworkResult<SyncPullResponsePayload> {
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::SyncPull, payload_bytes);
+
+        let response = self.send(peer, message).await?;
+
+        if response.message_type != MessageType::SyncPullResponse {
+            return Err(NetworkError::InvalidResponseType {
+                expected: "SyncPullResponse".to_string(),
+                got: format!("{:?}", response.message_type),
+            });
+        }
+
+        decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))
+    }
+
+    async fn send_availability_check(
+        &self,
+        peer: PeerId,
+        payload: AvailabilityCheckPayload,
+    ) -> NetworkResult<AvailabilityResultPayload> {
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::AvailabilityCheck, payload_bytes);
+
+        let response = self.send(peer, message).await?;
+
+        if response.message_type != MessageType::AvailabilityResult {
+            return Err(NetworkError::InvalidResponseType {
+                expected: "AvailabilityResult".to_string(),
+                got: format!("{:?}", response.message_type),
+            });
+        }
+
+        decode_payload(&response.payload).map_err(|e| NetworkError::Decoding(e.to_string()))
+    }
+
     async fn broadcast_settlement_confirm(
         &self,
         payload: SettleConfirmPayload,
@@ -677,6 +1258,67 @@ impl Network for NetworkNode {
         self.broadcast(message).await
     }
 
+    async fn broadcast_tombstone(&self, payload: TombstonePayload) -> NetworkResult<()> {
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::Tombstone, payload_bytes);
+        self.broadcast(message).await
+    }
+
+    async fn broadcast_report(&self, payload: ReportPayload) -> NetworkResult<()> {
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::Report, payload_bytes);
+        self.broadcast(message).await
+    }
+
+    async fn broadcast_revocation(&self, payload: RevocationPayload) -> NetworkResult<()> {
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::Revocation, payload_bytes);
+        self.broadcast(message).await
+    }
+
+    async fn broadcast_fraud_proof(&self, payload: FraudProofPayload) -> NetworkResult<()> {
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::FraudProof, payload_bytes);
+        self.broadcast(message).await
+    }
+
+    async fn broadcast_tag_announce(
+        &self,
+        tag: &str,
+        payload: AnnouncePayload,
+    ) -> NetworkResult<()> {
+        let topic = self
+            .config
+            .tag_topic(tag)
+            .ok_or_else(|| NetworkError::GossipSubError(format!("invalid tag: {:?}", tag)))?;
+        let payload_bytes =
+            encode_payload(&payload).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        let message = self.create_signed_message(MessageType::Announce, payload_bytes);
+        let data = encode_message(&message).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        self.record_replay(
+            ReplayDirection::Outbound,
+            ReplayKind::Broadcast,
+            &data,
+            |e| e.with_topic(&topic),
+        );
+
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send(SwarmCommand::GossipPublish {
+                topic,
+                data,
+                response: tx,
+            })
+            .await
+            .map_err(|_| NetworkError::ChannelClosed)?;
+
+        rx.await.map_err(|_| NetworkError::ChannelClosed)?
+    }
+
     fn connected_peers(&self) -> Vec<PeerId> {
         self.connected_peers_set
             .read()
@@ -713,7 +1355,30 @@ impl Network for NetworkNode {
 
     async fn next_event(&self) -> NetworkResult<NetworkEvent> {
         let mut event_rx = self.event_rx.lock().await;
-        event_rx.recv().await.ok_or(NetworkError::ChannelClosed)
+        let event = event_rx.recv().await.ok_or(NetworkError::ChannelClosed)?;
+        match &event {
+            NetworkEvent::InboundRequest {
+                peer,
+                request_id,
+                data,
+            } => {
+                if self.capture.is_some() {
+                    if let Ok(mut inbound_peers) = self.inbound_peers.lock() {
+                        inbound_peers.insert(*request_id, *peer);
+                    }
+                }
+                self.record_replay(ReplayDirection::Inbound, ReplayKind::Request, data, |e| {
+                    e.with_peer(*peer)
+                })
+            }
+            NetworkEvent::BroadcastReceived { topic, data } => {
+                self.record_replay(ReplayDirection::Inbound, ReplayKind::Broadcast, data, |e| {
+                    e.with_topic(topic)
+                })
+            }
+            _ => {}
+        }
+        Ok(event)
     }
 
     async fn send_response(
@@ -721,6 +1386,20 @@ impl Network for NetworkNode {
         request_id: libp2p::request_response::InboundRequestId,
         data: Vec<u8>,
     ) -> NetworkResult<()> {
+        let peer = self
+            .inbound_peers
+            .lock()
+            .ok()
+            .and_then(|mut inbound_peers| inbound_peers.remove(&request_id));
+        self.record_replay(
+            ReplayDirection::Outbound,
+            ReplayKind::Response,
+            &data,
+            |e| match peer {
+                Some(peer) => e.with_peer(peer),
+                None => e,
+            },
+        );
         self.command_tx
             .send(SwarmCommand::SendResponse { request_id, data })
             .await
@@ -735,8 +1414,9 @@ impl Network for NetworkNode {
     ) -> NetworkResult<()> {
         // Create a signed message
         let message = self.create_signed_message(message_type, payload);
-        // Encode to wire format
-        let data = encode_message(&message).map_err(|e| NetworkError::Encoding(e.to_string()))?;
+        // Encode to wire format, framing the (possibly large) payload in place
+        let data =
+            encode_message_owned(message).map_err(|e| NetworkError::Encoding(e.to_string()))?;
         // Send via existing send_response
         self.send_response(request_id, data).await
     }
@@ -760,6 +1440,20 @@ impl Network for NetworkNode {
         self.peer_mapper
             .register(libp2p_peer, nodalync_peer, placeholder_pubkey);
     }
+
+    fn relay_config(&self) -> RelayConfig {
+        self.config.relay.clone()
+    }
+
+    fn set_clock_offset(&self, offset_ms: i64) {
+        self.clock_offset.store(offset_ms, Ordering::Relaxed);
+    }
+
+    fn set_broadcast_backpressure(&self, engaged: bool) {
+        if self.broadcast_backpressure.swap(engaged, Ordering::Relaxed) != engaged {
+            debug!(engaged, "Broadcast backpressure changed");
+        }
+    }
 }
 
 /// Run the swarm event loop.
@@ -767,7 +1461,7 @@ async fn run_swarm(
     mut swarm: Swarm<NodalyncBehaviour>,
     mut command_rx: mpsc::Receiver<SwarmCommand>,
     event_tx: mpsc::Sender<NetworkEvent>,
-    ctx: SwarmContext,
+    mut ctx: SwarmContext,
 ) {
     // Subscribe to the announcement topic
     let topic = IdentTopic::new(&ctx.gossip_topic);
@@ -775,6 +1469,34 @@ async fn run_swarm(
         warn!("Failed to subscribe to gossipsub topic: {}", e);
     }
 
+    // Restore persisted DHT records
+    if let Some(ref path) = ctx.dht_record_path {
+        match dht_persist::load_records(path) {
+            Ok(records) => {
+                let count = records.len();
+                let store = swarm.behaviour_mut().kademlia.store_mut();
+                for record in records {
+                    if let Err(e) = store.put(record) {
+                        warn!("Failed to restore DHT record: {}", e);
+                    }
+                }
+                info!("Restored {} DHT records from {}", count, path.display());
+            }
+            Err(e) => warn!("Failed to load DHT records from {}: {}", path.display(), e),
+        }
+    }
+    let record_count = swarm.behaviour_mut().kademlia.store_mut().records().count();
+    ConnectionCounters::set(&ctx.counters.dht_records, record_count as u64);
+
+    // Whether the DHT store changed since it was last persisted
+    let mut dht_dirty = false;
+    let mut maintenance_interval = tokio::time::interval(MAINTENANCE_INTERVAL);
+
+    // Remote IPs, for per-IP request limits and connection accounting
+    let mut peer_ips: HashMap<PeerId, IpAddr> = HashMap::new();
+    let mut inbound_ips: HashMap<ConnectionId, IpAddr> = HashMap::new();
+    let mut rejected_connections: HashSet<ConnectionId> = HashSet::new();
+
     // Pending DHT operations
     let mut pending_dht_puts: HashMap<kad::QueryId, oneshot::Sender<NetworkResult<()>>> =
         HashMap::new();
@@ -795,6 +1517,12 @@ async fn run_swarm(
             event = swarm.select_next_some() => {
                 match event {
                     SwarmEvent::Behaviour(NodalyncBehaviourEvent::Kademlia(kad_event)) => {
+                        if matches!(
+                            kad_event,
+                            kad::Event::InboundRequest { request: kad::InboundRequest::PutRecord { .. } }
+                        ) {
+                            dht_dirty = true;
+                        }
                         handle_kademlia_event(
                             kad_event,
                             &mut pending_dht_puts,
@@ -803,6 +1531,26 @@ async fn run_swarm(
                     }
 
                     SwarmEvent::Behaviour(NodalyncBehaviourEvent::RequestResponse(rr_event)) => {
+                        if let request_response::Event::Message {
+                            peer,
+                            message: request_response::Message::Request { .. },
+                        } = &rr_event
+                        {
+                            let allowed = ctx.serve_requests
+                                && match (ctx.rate_limiter.as_mut(), peer_ips.get(peer)) {
+                                    (Some(limiter), Some(ip)) => {
+                                        limiter.admit_request(*ip, Instant::now())
+                                    }
+                                    _ => true,
+                                };
+                            if !allowed {
+                                // Dropping the event drops the response channel,
+                                // so the remote sees the request fail
+                                debug!("Dropping inbound request from {}", peer);
+                                ConnectionCounters::incr(&ctx.counters.rejected_requests_total);
+                                continue;
+                            }
+                        }
                         handle_request_response_event(
                             rr_event,
                             &ctx.pending_requests,
@@ -812,7 +1560,8 @@ async fn run_swarm(
                     }
 
                     SwarmEvent::Behaviour(NodalyncBehaviourEvent::Gossipsub(gs_event)) => {
-                        handle_gossipsub_event(gs_event, &event_tx).await;
+                        let shed = ctx.broadcast_backpressure.load(Ordering::Relaxed);
+                        handle_gossipsub_event(gs_event, &event_tx, shed, &ctx.counters).await;
                     }
 
                     SwarmEvent::Behaviour(NodalyncBehaviourEvent::Identify(id_event)) => {
@@ -823,30 +1572,69 @@ async fn run_swarm(
                         handle_ping_event(ping_event);
                     }
 
-                    SwarmEvent::ConnectionEstablished { peer_id, num_established, .. } => {
+                    SwarmEvent::ConnectionEstablished { peer_id, connection_id, endpoint, num_established, .. } => {
                         debug!("Connection established with {} (total: {})", peer_id, num_established);
-                        // Track connected peer
-                        if let Ok(mut peers) = ctx.connected_peers.write() {
-                            peers.insert(peer_id);
+                        let remote_ip = ip_of(endpoint.get_remote_address());
+
+                        if endpoint.is_listener() {
+                            // Enforce per-IP limits on inbound connections
+                            if let (Some(limiter), Some(ip)) = (ctx.rate_limiter.as_mut(), remote_ip) {
+                                if !limiter.admit_connection(ip, Instant::now()) {
+                                    debug!("Rate limit: closing inbound connection from {}", ip);
+                                    ConnectionCounters::incr(&ctx.counters.rejected_connections_total);
+                                    rejected_connections.insert(connection_id);
+                                    swarm.close_connection(connection_id);
+                                    continue;
+                                }
+                                inbound_ips.insert(connection_id, ip);
+                                ConnectionCounters::set(&ctx.counters.connected_ips, limiter.connected_ips() as u64);
+                            }
+                            ConnectionCounters::incr(&ctx.counters.inbound_total);
+                        } else {
+                            ConnectionCounters::incr(&ctx.counters.outbound_total);
                         }
-                        // Only send event on first connection
-                        if num_established.get() == 1 {
+                        ConnectionCounters::incr(&ctx.counters.active_connections);
+                        if let Some(ip) = remote_ip {
+                            peer_ips.insert(peer_id, ip);
+                        }
+
+                        // Track connected peer, sending the event on first connection
+                        let newly_connected = ctx
+                            .connected_peers
+                            .write()
+                            .map(|mut peers| peers.insert(peer_id))
+                            .unwrap_or(false);
+                        if newly_connected {
                             let _ = event_tx.send(NetworkEvent::PeerConnected { peer: peer_id }).await;
                         }
                     }
 
-                    SwarmEvent::ConnectionClosed { peer_id, num_established, cause, .. } => {
+                    SwarmEvent::ConnectionClosed { peer_id, connection_id, num_established, cause, .. } => {
                         debug!(
                             "Connection closed with {} (remaining: {}, cause: {:?})",
                             peer_id, num_established, cause
                         );
+                        if !rejected_connections.remove(&connection_id) {
+                            ConnectionCounters::decr(&ctx.counters.active_connections);
+                        }
+                        if let Some(ip) = inbound_ips.remove(&connection_id) {
+                            if let Some(limiter) = ctx.rate_limiter.as_mut() {
+                                limiter.connection_closed(ip);
+                                ConnectionCounters::set(&ctx.counters.connected_ips, limiter.connected_ips() as u64);
+                            }
+                        }
                         // Only unregister if no connections remain
                         if num_established == 0 {
-                            if let Ok(mut peers) = ctx.connected_peers.write() {
-                                peers.remove(&peer_id);
-                            }
+                            peer_ips.remove(&peer_id);
+                            let was_connected = ctx
+                                .connected_peers
+                                .write()
+                                .map(|mut peers| peers.remove(&peer_id))
+                                .unwrap_or(false);
                             ctx.peer_mapper.unregister(&peer_id);
-                            let _ = event_tx.send(NetworkEvent::PeerDisconnected { peer: peer_id }).await;
+                            if was_connected {
+                                let _ = event_tx.send(NetworkEvent::PeerDisconnected { peer: peer_id }).await;
+                            }
                         }
                     }
 
@@ -863,6 +1651,24 @@ async fn run_swarm(
                 }
             }
 
+            // Periodic maintenance
+            _ = maintenance_interval.tick() => {
+                if let Some(limiter) = ctx.rate_limiter.as_mut() {
+                    limiter.prune(Instant::now());
+                }
+                let store = swarm.behaviour_mut().kademlia.store_mut();
+                ConnectionCounters::set(&ctx.counters.dht_records, store.records().count() as u64);
+                if dht_dirty {
+                    if let Some(ref path) = ctx.dht_record_path {
+                        match persist_dht_records(&mut swarm, path) {
+                            Ok(count) => debug!("Persisted {} DHT records", count),
+                            Err(e) => warn!("Failed to persist DHT records: {}", e),
+                        }
+                    }
+                    dht_dirty = false;
+                }
+            }
+
             // Process commands
             Some(command) = command_rx.recv() => {
                 match command {
@@ -891,6 +1697,7 @@ async fn run_swarm(
                         let record = kad::Record::new(key, value);
                         match swarm.behaviour_mut().kademlia.put_record(record, kad::Quorum::One) {
                             Ok(query_id) => {
+                                dht_dirty = true;
                                 pending_dht_puts.insert(query_id, response);
                             }
                             Err(e) => {
@@ -909,6 +1716,7 @@ async fn run_swarm(
 
                     SwarmCommand::DhtRemove { key, response } => {
                         swarm.behaviour_mut().kademlia.remove_record(&RecordKey::new(&key));
+                        dht_dirty = true;
                         let _ = response.send(Ok(()));
                     }
 
@@ -963,12 +1771,30 @@ async fn run_swarm(
                             warn!("No response channel found for request {:?}", request_id);
                         }
                     }
+
+                    SwarmCommand::PersistDhtRecords { response } => {
+                        let result = match ctx.dht_record_path {
+                            Some(ref path) => persist_dht_records(&mut swarm, path),
+                            None => Ok(0),
+                        };
+                        if result.is_ok() {
+                            dht_dirty = false;
+                        }
+                        let _ = response.send(result);
+                    }
                 }
             }
         }
     }
 }
 
+/// Write the local DHT record store to `path`.
+fn persist_dht_records(swarm: &mut Swarm<NodalyncBehaviour>, path: &Path) -> NetworkResult<usize> {
+    let store = swarm.behaviour_mut().kademlia.store_mut();
+    let records: Vec<kad::Record> = store.records().map(|r| r.into_owned()).collect();
+    Ok(dht_persist::save_records(path, records.iter())?)
+}
+
 /// Handle Kademlia events.
 fn handle_kademlia_event(
     event: kad::Event,
@@ -1069,11 +1895,23 @@ async fn handle_request_response_event(
 }
 
 /// Handle GossipSub events.
+///
+/// With `shed_announcements` set, announcements are dropped instead of
+/// queued; other broadcasts, such as revocations, still pass.
 async fn handle_gossipsub_event(
     event: libp2p::gossipsub::Event,
     event_tx: &mpsc::Sender<NetworkEvent>,
+    shed_announcements: bool,
+    counters: &ConnectionCounters,
 ) {
     if let libp2p::gossipsub::Event::Message { message, .. } = event {
+        if shed_announcements
+            && decode_message(&message.data)
+                .is_ok_and(|message| message.message_type == MessageType::Announce)
+        {
+            ConnectionCounters::incr(&counters.dropped_broadcasts_total);
+            return;
+        }
         let _ = event_tx
             .send(NetworkEvent::BroadcastReceived {
                 topic: message.topic.to_string(),
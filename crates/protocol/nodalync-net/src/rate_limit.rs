@@ -0,0 +1,260 @@
+//! Per-IP rate limiting for inbound connections and requests.
+//!
+//! Public infrastructure nodes (bootstrap/relay) accept connections from
+//! anyone, so a single host must not be able to exhaust their connection
+//! slots or flood them with requests. The swarm task consults an
+//! [`IpRateLimiter`] when an inbound connection is established and when an
+//! inbound request arrives, closing the connection or dropping the request
+//! when the remote IP is over its limit.
+//!
+//! Rates use fixed one-minute windows per IP.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use libp2p::multiaddr::Protocol;
+use libp2p::Multiaddr;
+
+/// Length of a rate-limit window.
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Per-IP rate limits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    /// Maximum simultaneous inbound connections from one IP.
+    ///
+    /// Default: 4.
+    pub max_connections_per_ip: u32,
+
+    /// Maximum new inbound connections from one IP per minute.
+    ///
+    /// Default: 20.
+    pub connections_per_minute: u32,
+
+    /// Maximum inbound requests from one IP per minute.
+    ///
+    /// Default: 60.
+    pub requests_per_minute: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_connections_per_ip: 4,
+            connections_per_minute: 20,
+            requests_per_minute: 60,
+        }
+    }
+}
+
+/// Counter for a fixed window.
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    started: Instant,
+    count: u32,
+}
+
+impl Window {
+    fn new(now: Instant) -> Self {
+        Self {
+            started: now,
+            count: 0,
+        }
+    }
+
+    /// Count one event, returning false if the limit is already reached.
+    fn allow(&mut self, limit: u32, now: Instant) -> bool {
+        if now.duration_since(self.started) >= WINDOW {
+            *self = Self::new(now);
+        }
+        if self.count >= limit {
+            return false;
+        }
+        self.count += 1;
+        true
+    }
+
+    fn expired(&self, now: Instant) -> bool {
+        now.duration_since(self.started) >= WINDOW
+    }
+}
+
+/// State tracked for one remote IP.
+#[derive(Debug, Clone, Copy)]
+struct IpState {
+    active_connections: u32,
+    connections: Window,
+    requests: Window,
+}
+
+/// Per-IP connection and request rate limiter.
+#[derive(Debug)]
+pub struct IpRateLimiter {
+    config: RateLimitConfig,
+    ips: HashMap<IpAddr, IpState>,
+}
+
+impl IpRateLimiter {
+    /// Create a limiter with the given limits.
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            ips: HashMap::new(),
+        }
+    }
+
+    /// Get the configured limits.
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Admit a new inbound connection from `ip`.
+    ///
+    /// Returns false (and counts nothing) if the IP already has the maximum
+    /// number of open connections or has opened too many this minute. An
+    /// admitted connection must be released with [`connection_closed`].
+    ///
+    /// [`connection_closed`]: Self::connection_closed
+    pub fn admit_connection(&mut self, ip: IpAddr, now: Instant) -> bool {
+        let RateLimitConfig {
+            max_connections_per_ip,
+            connections_per_minute,
+            ..
+        } = self.config;
+        let state = self.state(ip, now);
+        if state.active_connections >= max_connections_per_ip {
+            return false;
+        }
+        if !state.connections.allow(connections_per_minute, now) {
+            return false;
+        }
+        state.active_connections += 1;
+        true
+    }
+
+    /// Release a connection previously admitted for `ip`.
+    pub fn connection_closed(&mut self, ip: IpAddr) {
+        if let Some(state) = self.ips.get_mut(&ip) {
+            state.active_connections = state.active_connections.saturating_sub(1);
+        }
+    }
+
+    /// Admit an inbound request from `ip`.
+    pub fn admit_request(&mut self, ip: IpAddr, now: Instant) -> bool {
+        let limit = self.config.requests_per_minute;
+        self.state(ip, now).requests.allow(limit, now)
+    }
+
+    /// Number of IPs with open connections.
+    pub fn connected_ips(&self) -> usize {
+        self.ips
+            .values()
+            .filter(|s| s.active_connections > 0)
+            .count()
+    }
+
+    /// Forget IPs with no open connections and no activity this window.
+    pub fn prune(&mut self, now: Instant) {
+        self.ips.retain(|_, s| {
+            s.active_connections > 0 || !s.connections.expired(now) || !s.requests.expired(now)
+        });
+    }
+
+    fn state(&mut self, ip: IpAddr, now: Instant) -> &mut IpState {
+        self.ips.entry(ip).or_insert_with(|| IpState {
+            active_connections: 0,
+            connections: Window::new(now),
+            requests: Window::new(now),
+        })
+    }
+}
+
+/// Extract the IP address from a multiaddr, if it has one.
+pub fn ip_of(addr: &Multiaddr) -> Option<IpAddr> {
+    addr.iter().find_map(|p| match p {
+        Protocol::Ip4(ip) => Some(IpAddr::V4(ip)),
+        Protocol::Ip6(ip) => Some(IpAddr::V6(ip)),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    fn ip(last: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(10, 0, 0, last))
+    }
+
+    #[test]
+    fn test_max_connections_per_ip() {
+        let mut limiter = IpRateLimiter::new(RateLimitConfig {
+            max_connections_per_ip: 2,
+            connections_per_minute: 100,
+            requests_per_minute: 100,
+        });
+        let now = Instant::now();
+
+        assert!(limiter.admit_connection(ip(1), now));
+        assert!(limiter.admit_connection(ip(1), now));
+        assert!(!limiter.admit_connection(ip(1), now));
+        // Other IPs are unaffected
+        assert!(limiter.admit_connection(ip(2), now));
+        assert_eq!(limiter.connected_ips(), 2);
+
+        limiter.connection_closed(ip(1));
+        assert!(limiter.admit_connection(ip(1), now));
+    }
+
+    #[test]
+    fn test_connection_rate_window() {
+        let mut limiter = IpRateLimiter::new(RateLimitConfig {
+            max_connections_per_ip: 100,
+            connections_per_minute: 2,
+            requests_per_minute: 100,
+        });
+        let now = Instant::now();
+
+        assert!(limiter.admit_connection(ip(1), now));
+        limiter.connection_closed(ip(1));
+        assert!(limiter.admit_connection(ip(1), now));
+        limiter.connection_closed(ip(1));
+        assert!(!limiter.admit_connection(ip(1), now));
+
+        // A new window resets the count
+        assert!(limiter.admit_connection(ip(1), now + WINDOW));
+    }
+
+    #[test]
+    fn test_request_rate_and_prune() {
+        let mut limiter = IpRateLimiter::new(RateLimitConfig {
+            max_connections_per_ip: 100,
+            connections_per_minute: 100,
+            requests_per_minute: 3,
+        });
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.admit_request(ip(1), now));
+        }
+        assert!(!limiter.admit_request(ip(1), now));
+        assert!(limiter.admit_request(ip(2), now));
+
+        limiter.prune(now + WINDOW);
+        assert!(limiter.ips.is_empty());
+    }
+
+    #[test]
+    fn test_ip_of() {
+        let addr: Multiaddr = "/ip4/192.168.1.7/tcp/9000".parse().unwrap();
+        assert_eq!(
+            ip_of(&addr),
+            Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 7)))
+        );
+
+        let addr: Multiaddr = "/dns4/example.com/tcp/9000".parse().unwrap();
+        assert_eq!(ip_of(&addr), None);
+    }
+}
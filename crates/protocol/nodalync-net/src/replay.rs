@@ -0,0 +1,431 @@
+//! Replay log of wire messages.
+//!
+//! Distributed bugs are hard to reproduce after the fact. With a replay log
+//! configured, the node records every wire message it sends and receives:
+//! requests, responses and broadcasts, each with the time it was seen, the
+//! clock offset in force, the peer or topic, and whether and as what it
+//! decoded. `nodalync replay` feeds the inbound messages back through the
+//! ops handlers.
+//!
+//! The file holds one JSON entry per line, with the encoded message in hex.
+//! It is capped: once it grows past `max_bytes`, the oldest entries are
+//! dropped so the newest half of the cap remains.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use nodalync_crypto::Timestamp;
+use nodalync_wire::{decode_message, MessageType};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Default cap on the log file size (16 MiB).
+pub const DEFAULT_REPLAY_LOG_MAX_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Where to keep a replay log, and how large it may grow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayLogConfig {
+    /// Log file.
+    pub path: PathBuf,
+
+    /// Size past which the oldest entries are dropped.
+    ///
+    /// Default: 16 MiB.
+    pub max_bytes: u64,
+}
+
+impl ReplayLogConfig {
+    /// Log to `path`, capped at the default size.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes: DEFAULT_REPLAY_LOG_MAX_BYTES,
+        }
+    }
+
+    /// Set the size cap in bytes.
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+}
+
+/// Which way a recorded message went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayDirection {
+    /// Received from a peer.
+    Inbound,
+    /// Sent by this node.
+    Outbound,
+}
+
+/// How a recorded message travelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayKind {
+    /// A request-response request.
+    Request,
+    /// A request-response response.
+    Response,
+    /// A GossipSub message.
+    Broadcast,
+}
+
+/// One recorded wire message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayEntry {
+    /// Position in the log, counting from the first entry ever recorded.
+    pub seq: u64,
+    /// When the message was seen, by the local (unadjusted) clock.
+    pub timestamp: Timestamp,
+    /// Clock offset applied to message timestamps at the time, in ms.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub clock_offset_ms: i64,
+    /// Which way the message went.
+    pub direction: ReplayDirection,
+    /// How the message travelled.
+    pub kind: ReplayKind,
+    /// The remote libp2p peer, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer: Option<String>,
+    /// The GossipSub topic, for broadcasts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    /// The decoded message type, if the message decoded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_type: Option<MessageType>,
+    /// Why the message didn't decode, if it didn't.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decode_error: Option<String>,
+    /// The encoded wire message.
+    #[serde(with = "hex_bytes")]
+    pub data: Vec<u8>,
+}
+
+impl ReplayEntry {
+    /// Record a message, decoding it for its type. The sequence number is
+    /// assigned by [`ReplayLog::record`].
+    pub fn new(
+        direction: ReplayDirection,
+        kind: ReplayKind,
+        data: &[u8],
+        timestamp: Timestamp,
+    ) -> Self {
+        let (message_type, decode_error) = match decode_message(data) {
+            Ok(message) => (Some(message.message_type), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        Self {
+            seq: 0,
+            timestamp,
+            clock_offset_ms: 0,
+            direction,
+            kind,
+            peer: None,
+            topic: None,
+            message_type,
+            decode_error,
+            data: data.to_vec(),
+        }
+    }
+
+    /// Set the remote peer.
+    pub fn with_peer(mut self, peer: libp2p::PeerId) -> Self {
+        self.peer = Some(peer.to_string());
+        self
+    }
+
+    /// Set the GossipSub topic.
+    pub fn with_topic(mut self, topic: impl Into<String>) -> Self {
+        self.topic = Some(topic.into());
+        self
+    }
+
+    /// Set the clock offset in force.
+    pub fn with_clock_offset(mut self, offset_ms: i64) -> Self {
+        self.clock_offset_ms = offset_ms;
+        self
+    }
+
+    /// The remote peer, if known and well-formed.
+    pub fn peer_id(&self) -> Option<libp2p::PeerId> {
+        self.peer.as_deref().and_then(|p| p.parse().ok())
+    }
+}
+
+/// An open replay log, appending entries.
+#[derive(Debug)]
+pub struct ReplayLog {
+    path: PathBuf,
+    max_bytes: u64,
+    file: File,
+    size: u64,
+    next_seq: u64,
+}
+
+impl ReplayLog {
+    /// Open the log, creating it if needed. Recording continues after the
+    /// entries already in it.
+    pub fn open(config: &ReplayLogConfig) -> io::Result<Self> {
+        if let Some(parent) = config.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let next_seq = read_replay_log(&config.path)?
+            .last()
+            .map_or(0, |entry| entry.seq + 1);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&config.path)?;
+        let mut size = file.metadata()?.len();
+
+        // Finish a line cut short by a crash, so it doesn't swallow the next
+        if size > 0 {
+            let mut last = [0u8];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                file.write_all(b"\n")?;
+                size += 1;
+            }
+        }
+        Ok(Self {
+            path: config.path.clone(),
+            max_bytes: config.max_bytes,
+            file,
+            size,
+            next_seq,
+        })
+    }
+
+    /// Append an entry, dropping the oldest ones if the log grows past its
+    /// cap.
+    pub fn record(&mut self, mut entry: ReplayEntry) -> io::Result<()> {
+        entry.seq = self.next_seq;
+        self.next_seq += 1;
+
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.size += line.len() as u64;
+
+        if self.size > self.max_bytes {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    /// Keep only the newest entries fitting in half the cap.
+    fn compact(&mut self) -> io::Result<()> {
+        let lines = BufReader::new(File::open(&self.path)?)
+            .lines()
+            .collect::<io::Result<Vec<_>>>()?;
+        let budget = self.max_bytes / 2;
+        let mut kept = 0u64;
+        let start = lines
+            .iter()
+            .rposition(|line| {
+                kept += line.len() as u64 + 1;
+                kept > budget
+            })
+            .map_or(0, |i| i + 1);
+
+        // Write aside and rename, so a crash never leaves a truncated log
+        let tmp_path = self.path.with_extension("tmp");
+        {
+            let mut tmp = File::create(&tmp_path)?;
+            for line in &lines[start..] {
+                tmp.write_all(line.as_bytes())?;
+                tmp.write_all(b"\n")?;
+            }
+            tmp.sync_all()?;
+        }
+        std::fs::rename(&tmp_path, &self.path)?;
+
+        self.file = OpenOptions::new().append(true).open(&self.path)?;
+        self.size = self.file.metadata()?.len();
+        Ok(())
+    }
+}
+
+/// Read every entry of a replay log, oldest first.
+///
+/// Returns an empty list if the file does not exist. Lines that don't
+/// parse, such as one cut short by a crash, are skipped with a warning.
+pub fn read_replay_log(path: &Path) -> io::Result<Vec<ReplayEntry>> {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut entries = Vec::new();
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => warn!(line = number + 1, error = %e, "Skipping unreadable replay log entry"),
+        }
+    }
+    Ok(entries)
+}
+
+fn is_zero(value: &i64) -> bool {
+    *value == 0
+}
+
+/// Hex encoding of the message bytes.
+mod hex_bytes {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        serializer.serialize_str(&hex)
+    }
+
+    #[allow(clippy::manual_is_multiple_of)]
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        if hex.len() % 2 != 0 || !hex.is_ascii() {
+            return Err(de::Error::custom("invalid hex length"));
+        }
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(de::Error::custom))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_wire::{create_message, encode_message};
+    use tempfile::TempDir;
+
+    fn ping(timestamp: Timestamp) -> Vec<u8> {
+        let (private_key, public_key) = generate_identity();
+        let message = create_message(
+            MessageType::Ping,
+            vec![1, 2, 3],
+            peer_id_from_public_key(&public_key),
+            timestamp,
+            &private_key,
+        );
+        encode_message(&message).unwrap()
+    }
+
+    #[test]
+    fn test_replay_log_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let config = ReplayLogConfig::new(temp.path().join("replay.log"));
+        let peer = libp2p::PeerId::random();
+
+        let mut log = ReplayLog::open(&config).unwrap();
+        let inbound = ReplayEntry::new(
+            ReplayDirection::Inbound,
+            ReplayKind::Request,
+            &ping(1_000),
+            1_500,
+        )
+        .with_peer(peer)
+        .with_clock_offset(-20);
+        log.record(inbound.clone()).unwrap();
+        log.record(
+            ReplayEntry::new(
+                ReplayDirection::Inbound,
+                ReplayKind::Broadcast,
+                b"junk",
+                2_000,
+            )
+            .with_topic("/nodalync/announce/1.0.0"),
+        )
+        .unwrap();
+        drop(log);
+
+        let entries = read_replay_log(&config.path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0], inbound);
+        assert_eq!(entries[0].message_type, Some(MessageType::Ping));
+        assert_eq!(entries[0].peer_id(), Some(peer));
+        assert_eq!(entries[1].seq, 1);
+        assert_eq!(entries[1].message_type, None);
+        assert!(entries[1].decode_error.is_some());
+
+        // Reopening continues the sequence
+        let mut log = ReplayLog::open(&config).unwrap();
+        log.record(ReplayEntry::new(
+            ReplayDirection::Outbound,
+            ReplayKind::Response,
+            &ping(3_000),
+            3_000,
+        ))
+        .unwrap();
+        assert_eq!(read_replay_log(&config.path).unwrap()[2].seq, 2);
+    }
+
+    #[test]
+    fn test_replay_log_capped() {
+        let temp = TempDir::new().unwrap();
+        let config = ReplayLogConfig::new(temp.path().join("replay.log")).with_max_bytes(4_096);
+        let mut log = ReplayLog::open(&config).unwrap();
+        let data = ping(1_000);
+        for i in 0..100 {
+            log.record(ReplayEntry::new(
+                ReplayDirection::Outbound,
+                ReplayKind::Broadcast,
+                &data,
+                i,
+            ))
+            .unwrap();
+        }
+
+        assert!(std::fs::metadata(&config.path).unwrap().len() <= 4_096);
+        let entries = read_replay_log(&config.path).unwrap();
+        assert!(!entries.is_empty());
+        // The newest entries are kept, in order
+        assert_eq!(entries.last().unwrap().seq, 99);
+        assert!(entries.windows(2).all(|w| w[1].seq == w[0].seq + 1));
+    }
+
+    #[test]
+    fn test_read_skips_truncated_entry() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("replay.log");
+        let mut log = ReplayLog::open(&ReplayLogConfig::new(&path)).unwrap();
+        log.record(ReplayEntry::new(
+            ReplayDirection::Inbound,
+            ReplayKind::Request,
+            &ping(1_000),
+            1_000,
+        ))
+        .unwrap();
+        drop(log);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"seq":1,"timest"#).unwrap();
+
+        assert_eq!(read_replay_log(&path).unwrap().len(), 1);
+
+        // Recording after the cut-off line still works
+        let mut log = ReplayLog::open(&ReplayLogConfig::new(&path)).unwrap();
+        log.record(ReplayEntry::new(
+            ReplayDirection::Inbound,
+            ReplayKind::Request,
+            &ping(2_000),
+            2_000,
+        ))
+        .unwrap();
+        let entries = read_replay_log(&path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].seq, 1);
+        assert!(read_replay_log(&temp.path().join("missing.log"))
+            .unwrap()
+            .is_empty());
+    }
+}
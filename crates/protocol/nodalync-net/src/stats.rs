@@ -0,0 +1,74 @@
+//! Connection statistics.
+//!
+//! The swarm task updates shared counters as connections open and close;
+//! [`NetworkNode::connection_stats`](crate::NetworkNode::connection_stats)
+//! returns a snapshot for metrics export.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Serialize;
+
+/// Snapshot of connection statistics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ConnectionStats {
+    /// Inbound connections accepted since startup.
+    pub inbound_total: u64,
+    /// Outbound connections established since startup.
+    pub outbound_total: u64,
+    /// Inbound connections closed by the rate limiter.
+    pub rejected_connections_total: u64,
+    /// Inbound requests dropped (rate limited or not served).
+    pub rejected_requests_total: u64,
+    /// Currently open connections.
+    pub active_connections: u64,
+    /// Distinct remote IPs with open inbound connections.
+    ///
+    /// Only tracked when rate limiting is enabled.
+    pub connected_ips: u64,
+    /// Records held in the local DHT store.
+    pub dht_records: u64,
+    /// Incoming announcements dropped under ingestion backpressure.
+    pub dropped_broadcasts_total: u64,
+}
+
+/// Counters shared between the swarm task and the node handle.
+#[derive(Debug, Default)]
+pub(crate) struct ConnectionCounters {
+    pub(crate) inbound_total: AtomicU64,
+    pub(crate) outbound_total: AtomicU64,
+    pub(crate) rejected_connections_total: AtomicU64,
+    pub(crate) rejected_requests_total: AtomicU64,
+    pub(crate) active_connections: AtomicU64,
+    pub(crate) connected_ips: AtomicU64,
+    pub(crate) dht_records: AtomicU64,
+    pub(crate) dropped_broadcasts_total: AtomicU64,
+}
+
+impl ConnectionCounters {
+    pub(crate) fn incr(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn decr(counter: &AtomicU64) {
+        let _ = counter.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| {
+            Some(v.saturating_sub(1))
+        });
+    }
+
+    pub(crate) fn set(counter: &AtomicU64, value: u64) {
+        counter.store(value, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> ConnectionStats {
+        ConnectionStats {
+            inbound_total: self.inbound_total.load(Ordering::Relaxed),
+            outbound_total: self.outbound_total.load(Ordering::Relaxed),
+            rejected_connections_total: self.rejected_connections_total.load(Ordering::Relaxed),
+            rejected_requests_total: self.rejected_requests_total.load(Ordering::Relaxed),
+            active_connections: self.active_connections.load(Ordering::Relaxed),
+            connected_ips: self.connected_ips.load(Ordering::Relaxed),
+            dht_records: self.dht_records.load(Ordering::Relaxed),
+            dropped_broadcasts_total: self.dropped_broadcasts_total.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -3,15 +3,24 @@
 //! This module defines the `Network` trait that provides the public API
 //! for P2P networking operations.
 
+use crate::config::RelayConfig;
 use crate::error::NetworkResult;
 use crate::event::NetworkEvent;
 use async_trait::async_trait;
 use libp2p::Multiaddr;
 use nodalync_crypto::{Hash, PeerId as NodalyncPeerId};
 use nodalync_wire::{
-    AnnouncePayload, ChannelClosePayload, ChannelOpenPayload, Message, MessageType,
-    PreviewRequestPayload, PreviewResponsePayload, QueryRequestPayload, QueryResponsePayload,
-    SearchPayload, SearchResponsePayload, SettleConfirmPayload,
+    AnnouncePayload, AvailabilityCheckPayload, AvailabilityResultPayload, ChannelClosePayload,
+    ChannelOpenPayload, ChannelSyncPayload, ChannelSyncResponsePayload, ChunkRequestPayload,
+    ChunkResponsePayload, FraudProofPayload, GroupRequestPayload, GroupResponsePayload,
+    InvoiceAckPayload, InvoicePayPayload, InvoicePayload, InvoiceRequestPayload, Message,
+    MessageType, PeerInfoPayload, PingPayload, PongPayload, PreviewRequestPayload,
+    PreviewResponsePayload, QueryRequestPayload, QueryResponsePayload, RelayQueryPayload,
+    RelayResponsePayload, ReportPayload, RevocationPayload, SearchPayload, SearchResponsePayload,
+    SettleConfirmPayload, SnapshotRequestPayload, SnapshotResponsePayload, Storefront,
+    SyncPullPayload, SyncPullResponsePayload, SyncPushAckPayload, SyncPushPayload,
+    TombstonePayload, UsageReportAckPayload, UsageReportPayload, VersionRequestPayload,
+    VersionResponsePayload,
 };
 
 /// The Network trait provides the public API for P2P networking.
@@ -45,6 +54,20 @@ pub trait Network: Send + Sync {
     /// This is a best-effort operation; DHT records may persist on other nodes.
     async fn dht_remove(&self, hash: &Hash) -> NetworkResult<()>;
 
+    /// Store a publisher's storefront in the DHT.
+    ///
+    /// Stored under `storefront_key` of its publisher, replacing any
+    /// earlier storefront there.
+    async fn dht_put_storefront(&self, storefront: Storefront) -> NetworkResult<()>;
+
+    /// Get a publisher's storefront from the DHT.
+    ///
+    /// Returned as found; the caller checks its signature and publisher.
+    async fn dht_get_storefront(
+        &self,
+        publisher: &NodalyncPeerId,
+    ) -> NetworkResult<Option<Storefront>>;
+
     // =========================================================================
     // Messaging
     // =========================================================================
@@ -86,6 +109,13 @@ pub trait Network: Send + Sync {
         request: SearchPayload,
     ) -> NetworkResult<SearchResponsePayload>;
 
+    /// Send a version request and receive the response.
+    async fn send_version_request(
+        &self,
+        peer: libp2p::PeerId,
+        request: VersionRequestPayload,
+    ) -> NetworkResult<VersionResponsePayload>;
+
     /// Send a channel open request.
     async fn send_channel_open(
         &self,
@@ -100,6 +130,117 @@ pub trait Network: Send + Sync {
         payload: ChannelClosePayload,
     ) -> NetworkResult<Message>;
 
+    /// Send a channel sync request and receive the peer's latest state.
+    async fn send_channel_sync(
+        &self,
+        peer: libp2p::PeerId,
+        payload: ChannelSyncPayload,
+    ) -> NetworkResult<ChannelSyncResponsePayload>;
+
+    /// Send an invoice to the peer expected to pay it.
+    async fn send_invoice(
+        &self,
+        peer: libp2p::PeerId,
+        payload: InvoicePayload,
+    ) -> NetworkResult<InvoiceAckPayload>;
+
+    /// Request an invoice from its payee.
+    ///
+    /// The response is an `Invoice` message, or an `InvoiceAck` rejecting
+    /// the request if the payee has no such invoice.
+    async fn request_invoice(
+        &self,
+        peer: libp2p::PeerId,
+        payload: InvoiceRequestPayload,
+    ) -> NetworkResult<Message>;
+
+    /// Send an invoice payment to its payee.
+    async fn send_invoice_payment(
+        &self,
+        peer: libp2p::PeerId,
+        payload: InvoicePayPayload,
+    ) -> NetworkResult<InvoiceAckPayload>;
+
+    /// Send an anonymized usage report to the publisher of queried content.
+    async fn send_usage_report(
+        &self,
+        peer: libp2p::PeerId,
+        payload: UsageReportPayload,
+    ) -> NetworkResult<UsageReportAckPayload>;
+
+    /// Send one layer of a relayed query to a relay.
+    ///
+    /// The reply comes back still encrypted with the reply key of every
+    /// relay on the route.
+    async fn send_relay_query(
+        &self,
+        peer: libp2p::PeerId,
+        payload: RelayQueryPayload,
+    ) -> NetworkResult<RelayResponsePayload>;
+
+    /// Request one chunk of chunked content from a provider.
+    ///
+    /// The chunk is returned as sent; the caller verifies it against the
+    /// manifest's chunk tree.
+    async fn request_chunk(
+        &self,
+        peer: libp2p::PeerId,
+        payload: ChunkRequestPayload,
+    ) -> NetworkResult<ChunkResponsePayload>;
+
+    /// Ping a peer.
+    ///
+    /// The pong carries the peer's clock, for clock skew estimation.
+    async fn send_ping(
+        &self,
+        peer: libp2p::PeerId,
+        payload: PingPayload,
+    ) -> NetworkResult<PongPayload>;
+
+    /// Send our peer info and receive the peer's in return.
+    ///
+    /// Exchanges addresses and advertised capabilities.
+    async fn send_peer_info(
+        &self,
+        peer: libp2p::PeerId,
+        payload: PeerInfoPayload,
+    ) -> NetworkResult<PeerInfoPayload>;
+
+    /// Request a group membership list from the group owner.
+    async fn request_group(
+        &self,
+        peer: libp2p::PeerId,
+        payload: GroupRequestPayload,
+    ) -> NetworkResult<GroupResponsePayload>;
+
+    /// Request a signed snapshot of a peer's announcements and peer list.
+    async fn request_snapshot(
+        &self,
+        peer: libp2p::PeerId,
+        payload: SnapshotRequestPayload,
+    ) -> NetworkResult<SnapshotResponsePayload>;
+
+    /// Leave an encrypted sync bundle with a peer for our other devices.
+    async fn push_sync_bundle(
+        &self,
+        peer: libp2p::PeerId,
+        payload: SyncPushPayload,
+    ) -> NetworkResult<SyncPushAckPayload>;
+
+    /// Fetch the sync bundle a peer holds for an owner.
+    async fn pull_sync_bundle(
+        &self,
+        peer: libp2p::PeerId,
+        payload: SyncPullPayload,
+    ) -> NetworkResult<SyncPullResponsePayload>;
+
+    /// Ask a peer to check whether our content is fetchable from outside.
+    async fn send_availability_check(
+        &self,
+        peer: libp2p::PeerId,
+        payload: AvailabilityCheckPayload,
+    ) -> NetworkResult<AvailabilityResultPayload>;
+
     /// Broadcast a settlement confirmation.
     async fn broadcast_settlement_confirm(
         &self,
@@ -112,6 +253,40 @@ pub trait Network: Send + Sync {
     /// allowing other nodes to discover newly published content.
     async fn broadcast_announce(&self, payload: AnnouncePayload) -> NetworkResult<()>;
 
+    /// Broadcast a content announcement on a tag's topic.
+    ///
+    /// Reaches nodes subscribed to the tag (see `NetworkConfig::tag_topic`)
+    /// in addition to the general announcement topic.
+    async fn broadcast_tag_announce(
+        &self,
+        tag: &str,
+        payload: AnnouncePayload,
+    ) -> NetworkResult<()>;
+
+    /// Broadcast a content tombstone.
+    ///
+    /// Uses GossipSub to broadcast a TOMBSTONE message on the announcement
+    /// topic, so nodes that saw the announcement also see the withdrawal.
+    async fn broadcast_tombstone(&self, payload: TombstonePayload) -> NetworkResult<()>;
+
+    /// Broadcast a content report.
+    ///
+    /// Uses GossipSub to broadcast a REPORT message on the announcement
+    /// topic. Each receiving node applies its own moderation policy.
+    async fn broadcast_report(&self, payload: ReportPayload) -> NetworkResult<()>;
+
+    /// Broadcast a key revocation certificate.
+    ///
+    /// Uses GossipSub to broadcast a REVOCATION message on the announcement
+    /// topic, so every node that listens for content hears about it.
+    async fn broadcast_revocation(&self, payload: RevocationPayload) -> NetworkResult<()>;
+
+    /// Broadcast a fraud proof against a provider.
+    ///
+    /// Uses GossipSub to broadcast a FRAUD_PROOF message on the
+    /// announcement topic. Each receiving node checks the proof itself.
+    async fn broadcast_fraud_proof(&self, payload: FraudProofPayload) -> NetworkResult<()>;
+
     // =========================================================================
     // Peer Management
     // =========================================================================
@@ -187,6 +362,21 @@ pub trait Network: Send + Sync {
     /// This is used when we learn a peer's Nodalync ID from a message
     /// (e.g., from a ChannelAccept response).
     fn register_peer_mapping(&self, libp2p_peer: libp2p::PeerId, nodalync_peer: NodalyncPeerId);
+
+    /// Get the relayed query configuration.
+    fn relay_config(&self) -> RelayConfig;
+
+    /// Correct the timestamps of outgoing messages by `offset_ms`.
+    ///
+    /// The offset is how far the local clock is estimated to be behind the
+    /// network (negative when ahead).
+    fn set_clock_offset(&self, offset_ms: i64);
+
+    /// Ask the network to shed incoming announcements while `engaged`.
+    ///
+    /// Set by the ops layer when its announcement ingestion buffer is full
+    /// and cleared once it drains. Other broadcasts are still delivered.
+    fn set_broadcast_backpressure(&self, engaged: bool);
 }
 
 #[cfg(test)]
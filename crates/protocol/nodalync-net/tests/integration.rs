@@ -112,6 +112,9 @@ async fn test_dht_announce_and_get() {
         price: 100,
         addresses: vec!["/ip4/127.0.0.1/tcp/9000".to_string()],
         publisher_peer_id: None,
+        publisher_key: None,
+        signature: None,
+        delegation: None,
     };
 
     // Node 1 announces
@@ -19,6 +19,9 @@ fn make_announce_payload(hash: Hash, title: &str) -> AnnouncePayload {
         price: 100,
         addresses: vec![],
         publisher_peer_id: None,
+        publisher_key: None,
+        signature: None,
+        delegation: None,
     }
 }
 
@@ -84,7 +84,7 @@ async fn test_two_node_publish_query() {
 
     // Create operations for node 1 (content creator)
     let temp_dir1 = TempDir::new().unwrap();
-    let mut ops1 = create_test_ops(&temp_dir1);
+    let ops1 = create_test_ops(&temp_dir1);
 
     // Create content on node 1
     let content = b"This is test content for multi-node scenario.";
@@ -102,6 +102,9 @@ async fn test_two_node_publish_query() {
         price: 100,
         addresses: vec![addr1.to_string()],
         publisher_peer_id: Some(node1.local_peer_id().to_string()),
+        publisher_key: None,
+        signature: None,
+        delegation: None,
     };
 
     // Node 1 announces content to DHT
@@ -173,9 +176,9 @@ async fn test_provenance_chain_three_nodes() {
     let temp_bob = TempDir::new().unwrap();
     let temp_carol = TempDir::new().unwrap();
 
-    let mut alice_ops = create_test_ops(&temp_alice);
-    let mut bob_ops = create_test_ops(&temp_bob);
-    let mut carol_ops = create_test_ops(&temp_carol);
+    let alice_ops = create_test_ops(&temp_alice);
+    let bob_ops = create_test_ops(&temp_bob);
+    let carol_ops = create_test_ops(&temp_carol);
 
     // --- Alice creates and publishes L0 content ---
     let alice_content = b"Alice's original research on distributed systems.";
@@ -263,7 +266,7 @@ async fn test_provenance_chain_three_nodes() {
 #[tokio::test]
 async fn test_economics_tracking() {
     let temp_dir = TempDir::new().unwrap();
-    let mut ops = create_test_ops(&temp_dir);
+    let ops = create_test_ops(&temp_dir);
 
     // Create content with price
     let content = b"Premium content worth paying for.";
@@ -291,7 +294,7 @@ async fn test_economics_tracking() {
 #[tokio::test]
 async fn test_l0_to_l3_derivation() {
     let temp_dir = TempDir::new().unwrap();
-    let mut ops = create_test_ops(&temp_dir);
+    let ops = create_test_ops(&temp_dir);
 
     // Create L0 (raw input)
     let l0_content = b"Raw source document with important information.";
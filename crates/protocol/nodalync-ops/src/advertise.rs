@@ -0,0 +1,219 @@
+//! Capability advertisement.
+//!
+//! Nodes tell each other what they support in PEER_INFO messages: serving
+//! content, payment channels, settlement, relaying, compressed version
+//! deltas, chunked transfers and so on (see [`Capability`]). What a peer
+//! advertises is kept in the peer store, and features that need the
+//! counterparty's cooperation are only used with peers that advertise
+//! them. Peers that haven't advertised anything yet are assumed to support
+//! everything, as nodes did before capabilities were advertised.
+
+use nodalync_crypto::PeerId;
+use nodalync_store::{ManifestFilter, ManifestStore, PeerStore};
+use nodalync_types::Visibility;
+use nodalync_valid::Validator;
+use nodalync_wire::{Capability, PeerInfoPayload};
+use tracing::debug;
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::NodeOperations;
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Capabilities this node advertises.
+    ///
+    /// Serving content, compressed version deltas and chunked transfers
+    /// are always available. The rest depend on what is attached: channels
+    /// need a private key, settlement a settlement layer, DHT indexing a
+    /// network, and relaying a network configured to relay.
+    pub fn local_capabilities(&self) -> Vec<Capability> {
+        let network = self.network();
+        Capability::ALL
+            .into_iter()
+            .filter(|capability| match capability {
+                Capability::Query | Capability::Compression | Capability::ChunkedTransfer => true,
+                Capability::Channel => self.private_key().is_some(),
+                Capability::Settle => self.settlement().is_some(),
+                Capability::Index => network.is_some(),
+                Capability::Relay => network.is_some_and(|n| n.relay_config().serve),
+                _ => false,
+            })
+            .collect()
+    }
+
+    /// The peer info we advertise.
+    pub fn local_peer_info(&self) -> OpsResult<PeerInfoPayload> {
+        let public_key = match self.private_key() {
+            Some(private_key) => private_key.public_key(),
+            None => self.state.identity.public_key()?,
+        };
+        let addresses = self
+            .network()
+            .map(|network| {
+                network
+                    .listen_addresses()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let shared = ManifestFilter::new()
+            .with_owner(self.peer_id())
+            .with_visibility(Visibility::Shared);
+
+        Ok(PeerInfoPayload {
+            peer_id: self.peer_id(),
+            public_key,
+            addresses,
+            capabilities: self.local_capabilities(),
+            content_count: self.state.manifests.list(shared)?.len() as u64,
+            uptime: self.started_at.elapsed().as_secs(),
+            did: Some(nodalync_crypto::did_from_public_key(&public_key)),
+        })
+    }
+
+    /// Exchange peer info with a connected peer.
+    ///
+    /// Sends ours, and records the peer's key, addresses and capabilities
+    /// from its answer. Returns the peer's info.
+    pub async fn exchange_peer_info(
+        &self,
+        peer: nodalync_net::PeerId,
+    ) -> OpsResult<PeerInfoPayload> {
+        let network = self
+            .network()
+            .cloned()
+            .ok_or_else(|| OpsError::invalid_operation("network not available"))?;
+        let info = network
+            .send_peer_info(peer, self.local_peer_info()?)
+            .await?;
+        self.handle_peer_info(&info)?;
+        network.register_peer_mapping(peer, info.peer_id);
+        debug!(peer = %info.peer_id, capabilities = ?info.capabilities, "Exchanged peer info");
+        Ok(info)
+    }
+
+    /// Whether a peer may be relied on for `capability`.
+    ///
+    /// True unless the peer advertised capabilities without it.
+    pub fn peer_supports(&self, peer: &PeerId, capability: Capability) -> bool {
+        match self.state.peers.get(peer) {
+            Ok(Some(info)) => info.supports(capability),
+            _ => true,
+        }
+    }
+
+    /// [`peer_supports`](Self::peer_supports) for a libp2p peer.
+    pub(crate) fn libp2p_peer_supports(
+        &self,
+        peer: &nodalync_net::PeerId,
+        capability: Capability,
+    ) -> bool {
+        self.network()
+            .and_then(|network| network.nodalync_peer_id(peer))
+            .is_none_or(|peer| self.peer_supports(&peer, capability))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_ops::DefaultNodeOperations;
+    use crate::OpsConfig;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_net::RelayConfig;
+    use nodalync_store::{NodeState, NodeStateConfig, PeerInfo};
+    use nodalync_test_utils::MockNetwork;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn create_test_ops(network: MockNetwork) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops = DefaultNodeOperations::with_config_and_network(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default(),
+            Arc::new(network),
+        );
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    fn remote_info(capabilities: Vec<Capability>) -> PeerInfoPayload {
+        let (_, public_key) = generate_identity();
+        PeerInfoPayload {
+            peer_id: peer_id_from_public_key(&public_key),
+            public_key,
+            addresses: vec!["/ip4/10.0.0.2/tcp/9000".to_string()],
+            capabilities,
+            content_count: 3,
+            uptime: 60,
+            did: None,
+        }
+    }
+
+    #[test]
+    fn test_local_capabilities() {
+        let (ops, _temp) = create_test_ops(MockNetwork::new());
+        let capabilities = ops.local_capabilities();
+        for capability in [
+            Capability::Query,
+            Capability::Channel,
+            Capability::Index,
+            Capability::Compression,
+            Capability::ChunkedTransfer,
+        ] {
+            assert!(capabilities.contains(&capability), "{capability}");
+        }
+        // No settlement layer, not relaying, no watchtower
+        assert!(!capabilities.contains(&Capability::Settle));
+        assert!(!capabilities.contains(&Capability::Relay));
+        assert!(!capabilities.contains(&Capability::Watchtower));
+
+        let relaying = MockNetwork::new().with_relay_config(RelayConfig::default().with_serve(0));
+        let (ops, _temp) = create_test_ops(relaying);
+        assert!(ops.local_capabilities().contains(&Capability::Relay));
+
+        let info = ops.local_peer_info().unwrap();
+        assert_eq!(info.peer_id, ops.peer_id());
+        assert_eq!(info.capabilities, ops.local_capabilities());
+        nodalync_valid::validate_peer_info(&info).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_exchange_peer_info_records_capabilities() {
+        let peer = nodalync_net::PeerId::random();
+        let remote = remote_info(vec![Capability::Query]);
+        let network = MockNetwork::new().with_peer_info(peer, remote.clone());
+        let (ops, _temp) = create_test_ops(network);
+
+        // Unknown peers are assumed to support everything
+        assert!(ops.peer_supports(&remote.peer_id, Capability::ChunkedTransfer));
+
+        let info = ops.exchange_peer_info(peer).await.unwrap();
+        assert_eq!(info, remote);
+        let stored = ops.state.peers.get(&remote.peer_id).unwrap().unwrap();
+        assert_eq!(stored.capabilities, Some(vec![Capability::Query]));
+        assert_eq!(stored.addresses, remote.addresses);
+        assert!(ops.peer_supports(&remote.peer_id, Capability::Query));
+        assert!(!ops.peer_supports(&remote.peer_id, Capability::ChunkedTransfer));
+        assert!(!ops.libp2p_peer_supports(&peer, Capability::ChunkedTransfer));
+    }
+
+    #[test]
+    fn test_peer_supports_known_peer_without_advertisement() {
+        let (ops, _temp) = create_test_ops(MockNetwork::new());
+        let remote = remote_info(vec![]);
+        ops.state
+            .peers
+            .upsert(&PeerInfo::new(remote.peer_id, remote.public_key, vec![], 0))
+            .unwrap();
+        assert!(ops.peer_supports(&remote.peer_id, Capability::Relay));
+    }
+}
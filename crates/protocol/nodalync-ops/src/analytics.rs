@@ -0,0 +1,377 @@
+//! Per-content access analytics.
+//!
+//! Every preview and paid query served for local content is recorded in the
+//! store's access log (see `OpsConfig::analytics`). This module records those
+//! accesses and aggregates them into `ContentStats` for publishers.
+//!
+//! With `hash_requesters` enabled, the log only keeps
+//! `H(our_peer_id || requester)`. Salting with our own ID keeps the hashes
+//! from being correlated across publishers, while the same consumer still
+//! hashes to the same value locally, so unique consumer counts stay exact.
+//!
+//! Consumers that opt in can also send usage reports after using content
+//! (see `OpsConfig::usage_reports`). Those are stored without the sender and
+//! summarized in `ContentStats::usage`.
+
+use std::collections::{BTreeMap, HashSet};
+
+use nodalync_crypto::{content_hash, Hash, PeerId, Timestamp};
+use nodalync_store::{AccessKind, AccessLogStore, AccessRecord, AccessRequester, UsageRecord};
+use nodalync_types::Amount;
+use nodalync_valid::Validator;
+use tracing::warn;
+
+use crate::error::OpsResult;
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+/// Length of a revenue bucket in milliseconds (one day).
+pub const STATS_BUCKET_MS: Timestamp = 86_400_000;
+
+/// Revenue and query count for one day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RevenueBucket {
+    /// Start of the day (milliseconds since epoch, UTC).
+    pub start: Timestamp,
+    /// Paid queries served that day.
+    pub queries: u64,
+    /// Revenue earned that day.
+    pub revenue: Amount,
+}
+
+/// Aggregated usage reports for one piece of content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UsageStats {
+    /// Usage reports received.
+    pub reports: u64,
+    /// Total bytes read across all reports.
+    pub bytes_read: u64,
+    /// Reports that included an outcome rating.
+    pub rated: u64,
+    /// Sum of all outcome ratings.
+    pub rating_sum: u64,
+    /// Reports that included a model context size.
+    pub with_context: u64,
+    /// Sum of all reported context sizes, in tokens.
+    pub context_tokens_sum: u64,
+    /// Most recent report.
+    pub last_report: Option<Timestamp>,
+}
+
+impl UsageStats {
+    /// Aggregate usage reports.
+    pub fn from_records(records: &[UsageRecord]) -> Self {
+        let mut stats = UsageStats::default();
+        for record in records {
+            stats.reports += 1;
+            stats.bytes_read = stats.bytes_read.saturating_add(record.bytes_read);
+            if let Some(rating) = record.rating {
+                stats.rated += 1;
+                stats.rating_sum += u64::from(rating);
+            }
+            if let Some(tokens) = record.context_tokens {
+                stats.with_context += 1;
+                stats.context_tokens_sum += u64::from(tokens);
+            }
+            stats.last_report = Some(
+                stats
+                    .last_report
+                    .map_or(record.timestamp, |t| t.max(record.timestamp)),
+            );
+        }
+        stats
+    }
+
+    /// Average outcome rating, if any report was rated.
+    pub fn average_rating(&self) -> Option<f64> {
+        (self.rated > 0).then(|| self.rating_sum as f64 / self.rated as f64)
+    }
+
+    /// Average bytes read per report.
+    pub fn average_bytes_read(&self) -> Option<u64> {
+        (self.reports > 0).then(|| self.bytes_read / self.reports)
+    }
+
+    /// Average model context size, in tokens, if any report included one.
+    pub fn average_context_tokens(&self) -> Option<u64> {
+        (self.with_context > 0).then(|| self.context_tokens_sum / self.with_context)
+    }
+}
+
+/// Aggregated access statistics for one piece of content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentStats {
+    /// Content hash.
+    pub hash: Hash,
+    /// Previews served.
+    pub previews: u64,
+    /// Queries served, paid or free.
+    pub queries: u64,
+    /// Queries of free content, served without a payment.
+    pub free_queries: u64,
+    /// Distinct peers that previewed or queried the content.
+    pub unique_consumers: u64,
+    /// Distinct peers that paid for the content.
+    pub unique_payers: u64,
+    /// Total revenue from recorded queries.
+    pub revenue: Amount,
+    /// First recorded access.
+    pub first_access: Option<Timestamp>,
+    /// Most recent recorded access.
+    pub last_access: Option<Timestamp>,
+    /// Revenue per day, oldest first. Days without queries are omitted.
+    pub revenue_by_day: Vec<RevenueBucket>,
+    /// Usage reported back by consumers.
+    pub usage: UsageStats,
+}
+
+impl ContentStats {
+    /// Aggregate access records for a content hash.
+    pub fn from_records(hash: Hash, records: &[AccessRecord]) -> Self {
+        let mut consumers = HashSet::new();
+        let mut payers = HashSet::new();
+        let mut buckets: BTreeMap<Timestamp, RevenueBucket> = BTreeMap::new();
+        let mut stats = ContentStats {
+            hash,
+            previews: 0,
+            queries: 0,
+            free_queries: 0,
+            unique_consumers: 0,
+            unique_payers: 0,
+            revenue: 0,
+            first_access: None,
+            last_access: None,
+            revenue_by_day: Vec::new(),
+            usage: UsageStats::default(),
+        };
+
+        for record in records {
+            consumers.insert(record.requester);
+            stats.first_access = Some(
+                stats
+                    .first_access
+                    .map_or(record.timestamp, |t| t.min(record.timestamp)),
+            );
+            stats.last_access = Some(
+                stats
+                    .last_access
+                    .map_or(record.timestamp, |t| t.max(record.timestamp)),
+            );
+
+            match record.kind {
+                AccessKind::Preview => stats.previews += 1,
+                AccessKind::Query => {
+                    stats.queries += 1;
+                    stats.revenue = stats.revenue.saturating_add(record.amount);
+                    if record.amount > 0 {
+                        payers.insert(record.requester);
+                    } else {
+                        stats.free_queries += 1;
+                    }
+
+                    let start = record.timestamp - record.timestamp % STATS_BUCKET_MS;
+                    let bucket = buckets.entry(start).or_insert(RevenueBucket {
+                        start,
+                        queries: 0,
+                        revenue: 0,
+                    });
+                    bucket.queries += 1;
+                    bucket.revenue = bucket.revenue.saturating_add(record.amount);
+                }
+            }
+        }
+
+        stats.unique_consumers = consumers.len() as u64;
+        stats.unique_payers = payers.len() as u64;
+        stats.revenue_by_day = buckets.into_values().collect();
+        stats
+    }
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Get access statistics for a piece of local content.
+    ///
+    /// Content that was never accessed (or unknown content) yields empty stats.
+    pub fn content_stats(&self, hash: &Hash) -> OpsResult<ContentStats> {
+        let records = self.state.access_log.for_content(hash)?;
+        let mut stats = ContentStats::from_records(*hash, &records);
+        stats.usage = UsageStats::from_records(&self.state.access_log.usage_for_content(hash)?);
+        Ok(stats)
+    }
+
+    /// Record a served preview or query in the access log.
+    ///
+    /// Analytics must never fail a request, so errors are only logged.
+    pub(crate) fn record_access(
+        &self,
+        requester: &PeerId,
+        hash: &Hash,
+        kind: AccessKind,
+        amount: Amount,
+    ) {
+        if !self.config.analytics.enabled {
+            return;
+        }
+
+        let requester = if self.config.analytics.hash_requesters {
+            AccessRequester::Hashed(content_hash(
+                &[self.peer_id().0.as_slice(), requester.0.as_slice()].concat(),
+            ))
+        } else {
+            AccessRequester::Peer(*requester)
+        };
+        let record = AccessRecord::new(*hash, requester, kind, amount, current_timestamp());
+
+        if let Err(e) = self.state.access_log.record(&record) {
+            warn!(hash = %hash, error = %e, "Failed to record content access");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AnalyticsConfig, OpsConfig};
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::NodeStateConfig;
+    use tempfile::TempDir;
+
+    fn create_test_ops(config: OpsConfig) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state_config = NodeStateConfig::new(temp_dir.path());
+        let state = nodalync_store::NodeState::open(state_config).unwrap();
+
+        let (_, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+
+        let ops = DefaultNodeOperations::with_config(state, peer_id, config);
+        (ops, temp_dir)
+    }
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    fn record(
+        requester: PeerId,
+        kind: AccessKind,
+        amount: Amount,
+        timestamp: Timestamp,
+    ) -> AccessRecord {
+        AccessRecord::new(
+            content_hash(b"content"),
+            AccessRequester::Peer(requester),
+            kind,
+            amount,
+            timestamp,
+        )
+    }
+
+    #[test]
+    fn test_stats_from_records() {
+        let alice = test_peer_id();
+        let bob = test_peer_id();
+        let day = STATS_BUCKET_MS;
+        let records = vec![
+            record(alice, AccessKind::Preview, 0, 100),
+            record(alice, AccessKind::Query, 10, 200),
+            record(bob, AccessKind::Preview, 0, day + 100),
+            record(bob, AccessKind::Query, 25, day + 200),
+            record(alice, AccessKind::Query, 10, day + 300),
+        ];
+
+        let stats = ContentStats::from_records(content_hash(b"content"), &records);
+        assert_eq!(stats.previews, 2);
+        assert_eq!(stats.queries, 3);
+        assert_eq!(stats.unique_consumers, 2);
+        assert_eq!(stats.unique_payers, 2);
+        assert_eq!(stats.revenue, 45);
+        assert_eq!(stats.first_access, Some(100));
+        assert_eq!(stats.last_access, Some(day + 300));
+        assert_eq!(
+            stats.revenue_by_day,
+            vec![
+                RevenueBucket {
+                    start: 0,
+                    queries: 1,
+                    revenue: 10,
+                },
+                RevenueBucket {
+                    start: day,
+                    queries: 2,
+                    revenue: 35,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record_access_hashes_requesters() {
+        let config = OpsConfig::default()
+            .with_analytics(AnalyticsConfig::default().with_hash_requesters(true));
+        let (ops, _temp) = create_test_ops(config);
+        let hash = content_hash(b"content");
+        let peer = test_peer_id();
+
+        ops.record_access(&peer, &hash, AccessKind::Preview, 0);
+        ops.record_access(&peer, &hash, AccessKind::Query, 40);
+
+        let records = ops.state.access_log.for_content(&hash).unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(records
+            .iter()
+            .all(|r| matches!(r.requester, AccessRequester::Hashed(_))));
+
+        // The same peer still counts once
+        let stats = ops.content_stats(&hash).unwrap();
+        assert_eq!(stats.unique_consumers, 1);
+        assert_eq!(stats.revenue, 40);
+    }
+
+    #[test]
+    fn test_record_access_disabled() {
+        let config =
+            OpsConfig::default().with_analytics(AnalyticsConfig::default().with_enabled(false));
+        let (ops, _temp) = create_test_ops(config);
+        let hash = content_hash(b"content");
+
+        ops.record_access(&test_peer_id(), &hash, AccessKind::Query, 40);
+
+        let stats = ops.content_stats(&hash).unwrap();
+        assert_eq!(stats.queries, 0);
+        assert_eq!(stats.first_access, None);
+    }
+
+    #[test]
+    fn test_usage_stats_from_records() {
+        let usage = |bytes_read, context_tokens, rating, timestamp| UsageRecord {
+            content_hash: content_hash(b"content"),
+            bytes_read,
+            context_tokens,
+            rating,
+            timestamp,
+        };
+        let stats = UsageStats::from_records(&[
+            usage(1_000, Some(8_000), Some(5), 300),
+            usage(3_000, None, Some(2), 100),
+            usage(2_000, Some(4_000), None, 200),
+        ]);
+
+        assert_eq!(stats.reports, 3);
+        assert_eq!(stats.bytes_read, 6_000);
+        assert_eq!(stats.average_bytes_read(), Some(2_000));
+        assert_eq!(stats.average_rating(), Some(3.5));
+        assert_eq!(stats.average_context_tokens(), Some(6_000));
+        assert_eq!(stats.last_report, Some(300));
+
+        let empty = UsageStats::from_records(&[]);
+        assert_eq!(empty.average_rating(), None);
+        assert_eq!(empty.average_bytes_read(), None);
+    }
+}
@@ -0,0 +1,431 @@
+//! Spam and abuse filtering for incoming announcements.
+//!
+//! Announcements received over GossipSub pass through a pipeline of filters
+//! before they are cached, in this order:
+//!
+//! 1. **Rate limit** - at most `max_per_peer_per_minute` announcements are
+//!    accepted from each sending peer per minute.
+//! 2. **Signature** - signed announcements must verify against the claimed
+//!    publisher (see `Validator::validate_announcement`).
+//! 3. **Withdrawn** - content its publisher has withdrawn with a tombstone
+//!    is not listed again.
+//! 4. **Revoked** - the publisher (or, for unsigned announcements, the
+//!    sending peer) must not have had its key revoked.
+//! 5. **Price** - the price must fall within `[min_price, max_price]`.
+//! 6. **Title** - the title must not contain a blocklisted word or phrase
+//!    (case-insensitive) or match a blocked regular expression.
+//! 7. **Reputation** - the publisher (or, for unsigned announcements, the
+//!    sending peer) must have at least `min_reputation`. Unknown peers
+//!    count as 0.
+//!
+//! Accepted announcements replace the publisher's earlier announcements
+//! with the same title when `collapse_duplicate_titles` is set, so a
+//! publisher re-announcing under one title shows up once in search.
+//!
+//! Accepted announcements are stored through the ingestion queue when it
+//! is running (see [`crate::ingest`]). Every outcome is counted in
+//! [`AnnouncementFilterStats`].
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use nodalync_crypto::PeerId;
+use nodalync_store::PeerStore;
+use nodalync_valid::Validator;
+use nodalync_wire::AnnouncePayload;
+use regex::Regex;
+use tracing::{debug, warn};
+
+use crate::config::AnnouncementFilterConfig;
+use crate::extraction::L1Extractor;
+use crate::node_ops::{lock, NodeOperations};
+
+/// Window for the per-peer rate limit.
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Counters for the incoming announcement filter since the node started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnnouncementFilterStats {
+    /// Announcements that passed every filter and were cached.
+    pub accepted: u64,
+    /// Dropped because the sending peer exceeded its rate limit.
+    pub dropped_rate_limited: u64,
+    /// Dropped because the publisher signature didn't verify.
+    pub dropped_invalid_signature: u64,
+    /// Dropped because the publisher withdrew the content.
+    pub dropped_withdrawn: u64,
+    /// Dropped because the publisher's key was revoked.
+    pub dropped_revoked: u64,
+    /// Dropped because the price was out of bounds.
+    pub dropped_price: u64,
+    /// Dropped because the title was blocklisted.
+    pub dropped_title: u64,
+    /// Dropped because the publisher's reputation was too low.
+    pub dropped_reputation: u64,
+    /// Earlier announcements replaced by a newer one with the same title.
+    pub collapsed_duplicates: u64,
+}
+
+impl AnnouncementFilterStats {
+    /// Total announcements dropped by any filter.
+    pub fn dropped_total(&self) -> u64 {
+        self.dropped_rate_limited
+            + self.dropped_invalid_signature
+            + self.dropped_withdrawn
+            + self.dropped_revoked
+            + self.dropped_price
+            + self.dropped_title
+            + self.dropped_reputation
+    }
+}
+
+/// Mutable state of the announcement filter.
+#[derive(Debug, Default)]
+pub(crate) struct AnnouncementFilterState {
+    stats: AnnouncementFilterStats,
+    /// Times of recently accepted announcements, per sending peer.
+    recent: HashMap<PeerId, VecDeque<Instant>>,
+    /// Compiled title patterns, with the source patterns they came from.
+    patterns: Option<(Vec<String>, Vec<Regex>)>,
+}
+
+impl AnnouncementFilterState {
+    /// Record an announcement from `sender`, returning false if it is over
+    /// the rate limit.
+    fn check_rate(&mut self, sender: &PeerId, max_per_minute: Option<u32>) -> bool {
+        let Some(max) = max_per_minute else {
+            return true;
+        };
+
+        let now = Instant::now();
+        self.recent.retain(|_, times| {
+            times
+                .back()
+                .is_some_and(|t| now.duration_since(*t) < RATE_LIMIT_WINDOW)
+        });
+
+        let times = self.recent.entry(*sender).or_default();
+        while times
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= RATE_LIMIT_WINDOW)
+        {
+            times.pop_front();
+        }
+        if times.len() >= max as usize {
+            return false;
+        }
+        times.push_back(now);
+        true
+    }
+
+    /// Get the compiled title patterns, recompiling if the configured
+    /// patterns have changed.
+    fn patterns(&mut self, config: &AnnouncementFilterConfig) -> &[Regex] {
+        let stale =
+            !matches!(&self.patterns, Some((source, _)) if *source == config.title_patterns);
+        if stale {
+            let compiled = config
+                .title_patterns
+                .iter()
+                .filter_map(|pattern| match Regex::new(pattern) {
+                    Ok(regex) => Some(regex),
+                    Err(e) => {
+                        warn!(pattern = %pattern, error = %e, "Ignoring invalid title pattern");
+                        None
+                    }
+                })
+                .collect();
+            self.patterns = Some((config.title_patterns.clone(), compiled));
+        }
+        self.patterns
+            .as_ref()
+            .map(|(_, compiled)| compiled.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Check a title against the blocklist and blocked patterns.
+    fn title_allowed(&mut self, title: &str, config: &AnnouncementFilterConfig) -> bool {
+        let lowered = title.to_lowercase();
+        let blocklisted = config
+            .title_blocklist
+            .iter()
+            .map(|word| word.trim().to_lowercase())
+            .any(|word| !word.is_empty() && lowered.contains(&word));
+        !blocklisted && !self.patterns(config).iter().any(|p| p.is_match(title))
+    }
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Get the announcement filter counters.
+    pub fn announcement_filter_stats(&self) -> AnnouncementFilterStats {
+        lock(&self.announcement_filter).stats
+    }
+
+    /// Run a broadcast announcement through the filter pipeline and cache
+    /// it if it passes.
+    ///
+    /// `sender` is the peer that signed the wire message. Returns whether
+    /// the announcement was stored.
+    pub(crate) fn filter_and_store_announcement(
+        &self,
+        sender: &PeerId,
+        payload: AnnouncePayload,
+    ) -> bool {
+        let config = &self.config.announcement_filter;
+        let mut filter = lock(&self.announcement_filter);
+
+        if !filter.check_rate(sender, config.max_per_peer_per_minute) {
+            debug!(hash = %payload.hash, sender = %sender, "Dropping rate-limited announcement");
+            filter.stats.dropped_rate_limited += 1;
+            return false;
+        }
+
+        let publisher = match self.validator.validate_announcement(&payload) {
+            Ok(publisher) => publisher,
+            Err(e) => {
+                warn!(hash = %payload.hash, error = %e, "Dropping announcement with invalid signature");
+                filter.stats.dropped_invalid_signature += 1;
+                return false;
+            }
+        };
+        drop(filter);
+
+        if self.is_withdrawn_by(&payload.hash, publisher.as_ref()) {
+            debug!(hash = %payload.hash, "Dropping announcement of withdrawn content");
+            lock(&self.announcement_filter).stats.dropped_withdrawn += 1;
+            return false;
+        }
+        if self.is_peer_revoked(&publisher.unwrap_or(*sender)) {
+            debug!(hash = %payload.hash, "Dropping announcement from revoked key");
+            lock(&self.announcement_filter).stats.dropped_revoked += 1;
+            return false;
+        }
+        let mut filter = lock(&self.announcement_filter);
+
+        if payload.price < config.min_price || payload.price > config.max_price {
+            debug!(hash = %payload.hash, price = payload.price, "Dropping announcement with out-of-range price");
+            filter.stats.dropped_price += 1;
+            return false;
+        }
+
+        if !filter.title_allowed(&payload.title, config) {
+            debug!(hash = %payload.hash, title = %payload.title, "Dropping announcement with blocked title");
+            filter.stats.dropped_title += 1;
+            return false;
+        }
+
+        if let Some(min_reputation) = config.min_reputation {
+            let peer = publisher.unwrap_or(*sender);
+            let reputation = self
+                .state
+                .peers
+                .get(&peer)
+                .ok()
+                .flatten()
+                .map(|info| info.reputation)
+                .unwrap_or(0);
+            if reputation < min_reputation {
+                debug!(hash = %payload.hash, peer = %peer, reputation, "Dropping announcement from low-reputation publisher");
+                filter.stats.dropped_reputation += 1;
+                return false;
+            }
+        }
+
+        if config.collapse_duplicate_titles {
+            let collapsed = self.state.remove_duplicate_announcements(&payload);
+            filter.stats.collapsed_duplicates += u64::from(collapsed);
+        }
+
+        debug!(hash = %payload.hash, publisher = ?publisher, "Announcement accepted");
+        filter.stats.accepted += 1;
+        drop(filter);
+        self.ingest_announcement(payload);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OpsConfig;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig, PeerInfo};
+    use nodalync_types::{ContentType, L1Summary};
+    use nodalync_valid::sign_announcement;
+    use tempfile::TempDir;
+
+    fn create_test_ops(filter: AnnouncementFilterConfig) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (_, public_key) = generate_identity();
+        let config = OpsConfig::default().with_announcement_filter(filter);
+        let ops =
+            DefaultNodeOperations::with_config(state, peer_id_from_public_key(&public_key), config);
+        (ops, temp_dir)
+    }
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    fn announcement(content: &[u8], title: &str, price: u64) -> AnnouncePayload {
+        let hash = content_hash(content);
+        AnnouncePayload {
+            hash,
+            content_type: ContentType::L0,
+            title: title.to_string(),
+            l1_summary: L1Summary::empty(hash),
+            price,
+            addresses: vec![],
+            publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_per_peer() {
+        let (ops, _temp) = create_test_ops(
+            AnnouncementFilterConfig::default().with_max_per_peer_per_minute(Some(2)),
+        );
+        let noisy = test_peer_id();
+        let quiet = test_peer_id();
+
+        for i in 0..4u8 {
+            ops.filter_and_store_announcement(
+                &noisy,
+                announcement(&[i], &format!("Post {}", i), 10),
+            );
+        }
+        assert!(ops.filter_and_store_announcement(&quiet, announcement(b"q", "Quiet", 10)));
+
+        let stats = ops.announcement_filter_stats();
+        assert_eq!(stats.accepted, 3);
+        assert_eq!(stats.dropped_rate_limited, 2);
+        assert_eq!(ops.state.announcement_count(), 3);
+    }
+
+    #[test]
+    fn test_price_and_title_filters() {
+        let (ops, _temp) = create_test_ops(
+            AnnouncementFilterConfig::default()
+                .with_price_range(5, 1_000)
+                .with_title_blocklist(vec!["Free Crypto".to_string()])
+                .with_title_patterns(vec![r"(?i)^buy\s+now".to_string(), "(".to_string()]),
+        );
+        let sender = test_peer_id();
+
+        let cases = [
+            (announcement(b"a", "Field Notes", 100), true),
+            (announcement(b"b", "Too Cheap", 1), false),
+            (announcement(b"c", "Too Dear", 5_000), false),
+            (announcement(b"d", "Get FREE crypto today", 100), false),
+            (announcement(b"e", "BUY  now!!!", 100), false),
+            (announcement(b"f", "Why buy now?", 100), true),
+        ];
+        for (payload, expected) in cases {
+            let title = payload.title.clone();
+            assert_eq!(
+                ops.filter_and_store_announcement(&sender, payload),
+                expected,
+                "{}",
+                title
+            );
+        }
+
+        let stats = ops.announcement_filter_stats();
+        assert_eq!(stats.accepted, 2);
+        assert_eq!(stats.dropped_price, 2);
+        assert_eq!(stats.dropped_title, 2);
+        assert_eq!(stats.dropped_total(), 4);
+    }
+
+    #[test]
+    fn test_min_reputation_uses_verified_publisher() {
+        let (ops, _temp) =
+            create_test_ops(AnnouncementFilterConfig::default().with_min_reputation(10));
+        let (_, relay_key) = generate_identity();
+        let relay = peer_id_from_public_key(&relay_key);
+        let (private_key, public_key) = generate_identity();
+        let publisher = peer_id_from_public_key(&public_key);
+
+        // Unknown peers count as 0
+        let mut signed = announcement(b"signed", "Signed", 10);
+        sign_announcement(&private_key, &mut signed).unwrap();
+        assert!(!ops.filter_and_store_announcement(&relay, signed.clone()));
+
+        // A well-regarded relay doesn't vouch for the publisher
+        ops.state
+            .peers
+            .upsert(&PeerInfo::new(relay, relay_key, vec![], 0).with_reputation(50))
+            .unwrap();
+        assert!(!ops.filter_and_store_announcement(&relay, signed.clone()));
+        // ...but does for its own unsigned announcements
+        assert!(ops.filter_and_store_announcement(&relay, announcement(b"own", "Own", 10)));
+
+        ops.state
+            .peers
+            .upsert(&PeerInfo::new(publisher, public_key, vec![], 0).with_reputation(10))
+            .unwrap();
+        assert!(ops.filter_and_store_announcement(&relay, signed));
+
+        assert_eq!(ops.announcement_filter_stats().dropped_reputation, 2);
+    }
+
+    #[test]
+    fn test_invalid_signature_counted() {
+        let (ops, _temp) = create_test_ops(AnnouncementFilterConfig::default());
+        let (private_key, _) = generate_identity();
+
+        let mut forged = announcement(b"forged", "Forged", 10);
+        sign_announcement(&private_key, &mut forged).unwrap();
+        forged.price = 1;
+
+        assert!(!ops.filter_and_store_announcement(&test_peer_id(), forged));
+        assert_eq!(ops.announcement_filter_stats().dropped_invalid_signature, 1);
+    }
+
+    #[test]
+    fn test_duplicate_titles_collapse() {
+        let (ops, _temp) = create_test_ops(AnnouncementFilterConfig::default());
+        let sender = test_peer_id();
+        let (private_key, _) = generate_identity();
+
+        let mut first = announcement(b"v1", "Weekly Digest", 10);
+        sign_announcement(&private_key, &mut first).unwrap();
+        let mut second = announcement(b"v2", "WEEKLY DIGEST", 10);
+        sign_announcement(&private_key, &mut second).unwrap();
+        // An unsigned copy can't evict the signed one
+        let impostor = announcement(b"v3", "Weekly Digest", 10);
+
+        for payload in [&first, &second, &impostor] {
+            assert!(ops.filter_and_store_announcement(&sender, payload.clone()));
+        }
+
+        assert!(ops.state.get_announcement(&first.hash).is_none());
+        assert!(ops.state.get_announcement(&second.hash).is_some());
+        assert!(ops.state.get_announcement(&impostor.hash).is_some());
+        assert_eq!(ops.announcement_filter_stats().collapsed_duplicates, 1);
+    }
+
+    #[test]
+    fn test_collapsing_disabled() {
+        let (ops, _temp) = create_test_ops(
+            AnnouncementFilterConfig::default().with_collapse_duplicate_titles(false),
+        );
+        let sender = test_peer_id();
+
+        ops.filter_and_store_announcement(&sender, announcement(b"v1", "Digest", 10));
+        ops.filter_and_store_announcement(&sender, announcement(b"v2", "Digest", 10));
+
+        assert_eq!(ops.state.announcement_count(), 2);
+        assert_eq!(ops.announcement_filter_stats().collapsed_duplicates, 0);
+    }
+}
@@ -0,0 +1,305 @@
+//! On-chain attestation of content provenance.
+//!
+//! An owner attests content by recording its hash and provenance root
+//! ([`compute_provenance_root`] of `root_L0L1`) through the settlement
+//! layer, and then marks the manifest's provenance as `attested`. The mark
+//! is only a claim: with a settlement handle, a node checks it against the
+//! chain before paying for derived content, since the provenance decides
+//! who that payment's revenue goes to. A claim that doesn't hold fails the
+//! query with [`ValidationError::AttestationMismatch`].
+//!
+//! Check results are cached per content hash and provenance root for
+//! [`OpsConfig::attestation_cache_ttl_ms`](crate::OpsConfig::attestation_cache_ttl_ms).
+//! Failed lookups are not cached.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use nodalync_crypto::Hash;
+use nodalync_econ::compute_provenance_root;
+use nodalync_settle::TransactionId;
+use nodalync_store::ManifestStore;
+use nodalync_types::Manifest;
+use nodalync_valid::{ValidationError, Validator};
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, lock, NodeOperations};
+
+/// Outcome of checking a manifest's attestation claim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttestationStatus {
+    /// The manifest doesn't claim an attestation
+    Unclaimed,
+    /// The manifest claims an attestation, but there is no settlement
+    /// layer to check it against
+    Unverified,
+    /// The on-chain attestation matches the content and provenance root
+    Verified,
+}
+
+/// Cached results of attestation checks.
+#[derive(Debug, Default)]
+pub(crate) struct AttestationCache {
+    entries: HashMap<Hash, CachedCheck>,
+}
+
+#[derive(Debug)]
+struct CachedCheck {
+    /// Provenance root the check was made for
+    provenance_root: Hash,
+    /// `Err` holds the mismatch reason
+    result: Result<(), String>,
+    checked_at: Instant,
+}
+
+impl AttestationCache {
+    fn get(
+        &self,
+        hash: &Hash,
+        provenance_root: &Hash,
+        ttl: Duration,
+    ) -> Option<&Result<(), String>> {
+        self.entries
+            .get(hash)
+            .filter(|c| c.provenance_root == *provenance_root && c.checked_at.elapsed() < ttl)
+            .map(|c| &c.result)
+    }
+
+    fn insert(&mut self, hash: Hash, provenance_root: Hash, result: Result<(), String>) {
+        self.entries.insert(
+            hash,
+            CachedCheck {
+                provenance_root,
+                result,
+                checked_at: Instant::now(),
+            },
+        );
+    }
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Attest our content on-chain and claim the attestation in its manifest.
+    ///
+    /// Records the content hash and its provenance root through the
+    /// settlement layer.
+    pub async fn attest_content(&self, hash: &Hash) -> OpsResult<TransactionId> {
+        let mut manifest = self
+            .state
+            .manifests
+            .load(hash)?
+            .ok_or(OpsError::ManifestNotFound(*hash))?;
+        if manifest.owner != self.peer_id() {
+            return Err(OpsError::AccessDenied);
+        }
+        let settlement = self
+            .settlement()
+            .cloned()
+            .ok_or(OpsError::SettlementRequired)?;
+
+        let provenance_root = compute_provenance_root(&manifest.provenance.root_l0l1);
+        let tx_id = settlement
+            .attest(hash, &provenance_root)
+            .await
+            .map_err(|e| OpsError::SettlementFailed(format!("attestation failed: {}", e)))?;
+
+        manifest.provenance.attested = true;
+        manifest.updated_at = current_timestamp();
+        self.state.manifests.update(&manifest)?;
+        lock(&self.attestation_cache).insert(*hash, provenance_root, Ok(()));
+
+        Ok(tx_id)
+    }
+
+    /// Check a manifest's attestation claim against the chain.
+    ///
+    /// Without a claim, or without a settlement layer, there is nothing to
+    /// check. Results are cached; see the module docs.
+    ///
+    /// # Errors
+    /// - `Validation(AttestationMismatch)` if there is no attestation for the
+    ///   content, or it records another provenance root
+    /// - `SettlementFailed` if the attestation can't be looked up
+    pub async fn verify_attestation(&self, manifest: &Manifest) -> OpsResult<AttestationStatus> {
+        if !manifest.provenance.attested {
+            return Ok(AttestationStatus::Unclaimed);
+        }
+        let Some(settlement) = self.settlement().cloned() else {
+            return Ok(AttestationStatus::Unverified);
+        };
+
+        let provenance_root = compute_provenance_root(&manifest.provenance.root_l0l1);
+        let ttl = Duration::from_millis(self.config.attestation_cache_ttl_ms);
+        let cached = lock(&self.attestation_cache)
+            .get(&manifest.hash, &provenance_root, ttl)
+            .cloned();
+        let result = match cached {
+            Some(cached) => cached,
+            None => {
+                let attestation = settlement
+                    .get_attestation(&manifest.hash)
+                    .await
+                    .map_err(|e| OpsError::SettlementFailed(e.to_string()))?;
+                let result = match attestation {
+                    None => Err("no attestation on-chain".to_string()),
+                    Some(a) if a.content_hash != manifest.hash => {
+                        Err(format!("attestation is for {}", a.content_hash))
+                    }
+                    Some(a) if a.provenance_root != provenance_root => Err(format!(
+                        "on-chain provenance root {} does not match the manifest's {}",
+                        a.provenance_root, provenance_root
+                    )),
+                    Some(_) => Ok(()),
+                };
+                lock(&self.attestation_cache).insert(
+                    manifest.hash,
+                    provenance_root,
+                    result.clone(),
+                );
+                result
+            }
+        };
+
+        result
+            .map(|()| AttestationStatus::Verified)
+            .map_err(|reason| {
+                tracing::warn!(hash = %manifest.hash, "Attestation claim rejected: {}", reason);
+                OpsError::Validation(ValidationError::AttestationMismatch { reason })
+            })
+    }
+
+    /// Check the attestation claim of derived content before paying for it.
+    ///
+    /// The provenance of derived content decides who the payment's revenue
+    /// goes to, so a claim that doesn't hold stops the payment.
+    pub(crate) async fn check_revenue_claim(&self, manifest: &Manifest) -> OpsResult<()> {
+        if manifest.provenance.is_derived() {
+            self.verify_attestation(manifest).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_settle::Settlement;
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_test_utils::MockSettlement;
+    use nodalync_types::Metadata;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (_, public_key) = generate_identity();
+        let ops = DefaultNodeOperations::with_defaults(state, peer_id_from_public_key(&public_key));
+        (ops, temp_dir)
+    }
+
+    fn derive(ops: &mut DefaultNodeOperations) -> Hash {
+        let a = ops
+            .create_content(b"Source A", Metadata::new("A", 8))
+            .unwrap();
+        let b = ops
+            .create_content(b"Source B", Metadata::new("B", 8))
+            .unwrap();
+        ops.derive_content(&[a, b], b"Insight", Metadata::new("Insight", 7))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_attest_and_verify() {
+        let (mut ops, _temp) = create_test_ops();
+        let hash = derive(&mut ops);
+
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        assert_eq!(
+            ops.verify_attestation(&manifest).await.unwrap(),
+            AttestationStatus::Unclaimed
+        );
+        assert!(matches!(
+            ops.attest_content(&hash).await,
+            Err(OpsError::SettlementRequired)
+        ));
+
+        let settlement = Arc::new(MockSettlement::new());
+        ops.set_settlement(settlement.clone());
+        ops.attest_content(&hash).await.unwrap();
+        assert_eq!(settlement.attestation_count(), 1);
+
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        assert!(manifest.provenance.attested);
+        assert_eq!(
+            ops.verify_attestation(&manifest).await.unwrap(),
+            AttestationStatus::Verified
+        );
+        ops.check_revenue_claim(&manifest).await.unwrap();
+
+        // Without a settlement layer the claim can't be checked
+        ops.clear_settlement();
+        assert_eq!(
+            ops.verify_attestation(&manifest).await.unwrap(),
+            AttestationStatus::Unverified
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mismatched_claims_rejected() {
+        let (mut ops, _temp) = create_test_ops();
+        let hash = derive(&mut ops);
+        let settlement = Arc::new(MockSettlement::new());
+        ops.set_settlement(settlement.clone());
+
+        // Claimed, but never attested
+        let mut manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        manifest.provenance.attested = true;
+        let result = ops.check_revenue_claim(&manifest).await;
+        assert!(matches!(
+            result,
+            Err(OpsError::Validation(
+                ValidationError::AttestationMismatch { .. }
+            ))
+        ));
+
+        // Attested with another provenance root: a chain that redirects
+        // revenue doesn't match
+        settlement
+            .attest(&hash, &content_hash(b"other root"))
+            .await
+            .unwrap();
+        manifest.provenance.root_l0l1[0].weight += 1;
+        assert!(ops.verify_attestation(&manifest).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_results_are_cached() {
+        let (mut ops, _temp) = create_test_ops();
+        let hash = derive(&mut ops);
+        let settlement = Arc::new(MockSettlement::new());
+        ops.set_settlement(settlement.clone());
+        ops.attest_content(&hash).await.unwrap();
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+
+        // The chain now disagrees, but the cached result stands until it
+        // expires
+        settlement
+            .attest(&hash, &content_hash(b"other root"))
+            .await
+            .unwrap();
+        assert_eq!(
+            ops.verify_attestation(&manifest).await.unwrap(),
+            AttestationStatus::Verified
+        );
+
+        ops.config.attestation_cache_ttl_ms = 0;
+        assert!(ops.verify_attestation(&manifest).await.is_err());
+    }
+}
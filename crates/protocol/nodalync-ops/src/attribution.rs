@@ -0,0 +1,272 @@
+//! Attribution certificates for derived content.
+//!
+//! An owner issues a signed [`AttributionCertificate`] for one of its L3
+//! items: the provenance chain with the weight of every root source, and
+//! references to the on-chain attestations of the content and its sources
+//! when a settlement layer is configured. Owners of root sources can add
+//! their signatures to endorse it. Anyone can verify a certificate offline;
+//! a node holding the manifest also checks it against the local chain.
+
+use std::collections::HashSet;
+
+use nodalync_crypto::{Hash, PeerId};
+use nodalync_store::ManifestStore;
+use nodalync_types::{AttestationReference, AttributionCertificate, ContentType};
+use nodalync_valid::{
+    sign_attribution_certificate, validate_attribution_certificate, ValidationError, Validator,
+};
+use tracing::warn;
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Issue a signed attribution certificate for our own L3 content.
+    ///
+    /// Attestations are looked up for the content and each root source;
+    /// lookup failures leave them out rather than failing the certificate.
+    pub async fn attribution_certificate(&self, hash: &Hash) -> OpsResult<AttributionCertificate> {
+        let manifest = self
+            .state
+            .manifests
+            .load(hash)?
+            .ok_or(OpsError::ManifestNotFound(*hash))?;
+        if manifest.owner != self.peer_id() {
+            return Err(OpsError::AccessDenied);
+        }
+        if manifest.content_type != ContentType::L3 || !manifest.provenance.is_derived() {
+            return Err(OpsError::invalid_operation(
+                "attribution certificates are only issued for derived (L3) content",
+            ));
+        }
+        let private_key = self.private_key().ok_or(OpsError::PrivateKeyRequired)?;
+
+        let mut attested = vec![*hash];
+        let mut seen = HashSet::from([*hash]);
+        for entry in &manifest.provenance.root_l0l1 {
+            if seen.insert(entry.hash) {
+                attested.push(entry.hash);
+            }
+        }
+        let attestations = self.attestation_references(&attested).await;
+
+        let now = current_timestamp();
+        let mut certificate = AttributionCertificate::new(
+            *hash,
+            manifest.metadata.title.clone(),
+            &private_key.public_key(),
+            &manifest.provenance,
+            attestations,
+            now,
+        );
+        sign_attribution_certificate(private_key, &mut certificate, now);
+        Ok(certificate)
+    }
+
+    /// Endorse another owner's certificate as the owner of a root source.
+    pub fn endorse_attribution_certificate(
+        &self,
+        certificate: &mut AttributionCertificate,
+    ) -> OpsResult<()> {
+        validate_attribution_certificate(certificate)?;
+        if !certificate
+            .sources
+            .iter()
+            .any(|source| source.owner == self.peer_id())
+        {
+            return Err(OpsError::invalid_operation(
+                "we own none of the certificate's sources",
+            ));
+        }
+        let private_key = self.private_key().ok_or(OpsError::PrivateKeyRequired)?;
+        sign_attribution_certificate(private_key, certificate, current_timestamp());
+        Ok(())
+    }
+
+    /// Verify an attribution certificate and return its signers.
+    ///
+    /// Beyond [`validate_attribution_certificate`], a certificate for
+    /// content whose manifest we hold must match its owner and provenance.
+    pub fn verify_attribution_certificate(
+        &self,
+        certificate: &AttributionCertificate,
+    ) -> OpsResult<Vec<PeerId>> {
+        let signers = validate_attribution_certificate(certificate)?;
+
+        if let Some(manifest) = self.state.manifests.load(&certificate.content_hash)? {
+            let roots: Vec<_> = manifest
+                .provenance
+                .root_l0l1
+                .iter()
+                .map(|entry| (entry.hash, entry.owner, entry.weight))
+                .collect();
+            let sources: Vec<_> = certificate
+                .sources
+                .iter()
+                .map(|source| (source.content_hash, source.owner, source.weight))
+                .collect();
+            if manifest.owner != certificate.owner
+                || manifest.provenance.derived_from != certificate.derived_from
+                || roots != sources
+            {
+                return Err(OpsError::Validation(
+                    ValidationError::InvalidAttributionCertificate {
+                        reason: "does not match the local provenance chain".to_string(),
+                    },
+                ));
+            }
+        }
+
+        Ok(signers)
+    }
+
+    /// Look up on-chain attestations, skipping hashes without one.
+    async fn attestation_references(&self, hashes: &[Hash]) -> Vec<AttestationReference> {
+        let Some(settlement) = self.settlement() else {
+            return Vec::new();
+        };
+
+        let mut references = Vec::new();
+        for hash in hashes {
+            match settlement.get_attestation(hash).await {
+                Ok(Some(attestation)) => references.push(AttestationReference {
+                    content_hash: attestation.content_hash,
+                    provenance_root: attestation.provenance_root,
+                    account: attestation.owner.to_string(),
+                    timestamp: attestation.timestamp,
+                }),
+                Ok(None) => {}
+                Err(e) => {
+                    warn!(hash = %hash, "Attestation lookup failed, leaving it out of the certificate: {}", e)
+                }
+            }
+        }
+        references
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_settle::Settlement;
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_test_utils::MockSettlement;
+    use nodalync_types::Metadata;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops =
+            DefaultNodeOperations::with_defaults(state, peer_id_from_public_key(&public_key));
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    fn create(ops: &mut DefaultNodeOperations, content: &[u8]) -> Hash {
+        let meta = Metadata::new("Source", content.len() as u64);
+        ops.create_content(content, meta).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_issue_and_verify_certificate() {
+        let (mut ops, _temp) = create_test_ops();
+        let a = create(&mut ops, b"Source A");
+        let b = create(&mut ops, b"Source B");
+        let insight = b"Insight from A and B";
+        let l3 = ops
+            .derive_content(
+                &[a, b],
+                insight,
+                Metadata::new("Insight", insight.len() as u64),
+            )
+            .unwrap();
+
+        // Only derived content gets a certificate
+        assert!(ops.attribution_certificate(&a).await.is_err());
+
+        let settlement = Arc::new(MockSettlement::new());
+        settlement.attest(&a, &a).await.unwrap();
+        ops.set_settlement(settlement);
+
+        let certificate = ops.attribution_certificate(&l3).await.unwrap();
+        assert_eq!(certificate.title, "Insight");
+        assert_eq!(certificate.derived_from, vec![a, b]);
+        assert_eq!(certificate.sources.len(), 2);
+        assert_eq!(certificate.attestations.len(), 1);
+        assert_eq!(certificate.attestations[0].content_hash, a);
+        assert_eq!(
+            ops.verify_attribution_certificate(&certificate).unwrap(),
+            vec![ops.peer_id()]
+        );
+
+        // Survives a JSON-LD roundtrip
+        let json = serde_json::to_string(&certificate).unwrap();
+        let parsed: AttributionCertificate = serde_json::from_str(&json).unwrap();
+        assert!(ops.verify_attribution_certificate(&parsed).is_ok());
+
+        // A validly signed certificate that disagrees with the local chain
+        let mut forged = certificate.clone();
+        forged.sources.pop();
+        forged.proofs.clear();
+        sign_attribution_certificate(ops.private_key().unwrap(), &mut forged, 0);
+        assert!(matches!(
+            ops.verify_attribution_certificate(&forged),
+            Err(OpsError::Validation(
+                ValidationError::InvalidAttributionCertificate { .. }
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_source_owner_endorsement() {
+        let (ops, _temp) = create_test_ops();
+        let (mut source_ops, _source_temp) = create_test_ops();
+        let source = create(&mut source_ops, b"Their source");
+        let manifest = source_ops.get_content_manifest(&source).unwrap().unwrap();
+        ops.state.manifests.store(&manifest).unwrap();
+
+        let insight = b"Insight from their source";
+        let l3 = ops
+            .derive_content(
+                &[source],
+                insight,
+                Metadata::new("Insight", insight.len() as u64),
+            )
+            .unwrap();
+        let mut certificate = ops.attribution_certificate(&l3).await.unwrap();
+
+        // Someone who owns no source can't endorse it
+        let (stranger, _stranger_temp) = create_test_ops();
+        assert!(stranger
+            .endorse_attribution_certificate(&mut certificate.clone())
+            .is_err());
+
+        source_ops
+            .endorse_attribution_certificate(&mut certificate)
+            .unwrap();
+        assert_eq!(
+            source_ops
+                .verify_attribution_certificate(&certificate)
+                .unwrap(),
+            vec![ops.peer_id(), source_ops.peer_id()]
+        );
+
+        // Certificates for content we don't own aren't issued
+        assert!(matches!(
+            source_ops
+                .attribution_certificate(&content_hash(insight))
+                .await,
+            Err(OpsError::ManifestNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,481 @@
+//! Content availability health checks.
+//!
+//! Content a publisher marks Shared may still be unreachable from outside:
+//! behind NAT, on an address nobody can dial, or served by a node that
+//! fails previews. [`check_availability`](NodeOperations::check_availability)
+//! asks a remote peer to dial us at our listen addresses and preview a
+//! sample of our shared content, and records the outcome per hash in the
+//! availability store. Content failing
+//! [`alert_after`](crate::AvailabilityConfig::alert_after) checks in a row
+//! is reported as [`OpsEvent::ContentUnreachable`], which also reaches
+//! webhooks.
+//!
+//! The other side is
+//! [`handle_availability_check`](NodeOperations::handle_availability_check):
+//! a checker only previews content owned by the peer asking, so it can't
+//! be used to probe third parties.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nodalync_crypto::{Hash, PeerId};
+use nodalync_net::{Multiaddr, Network};
+use nodalync_store::{
+    AvailabilityCheck, AvailabilityRecord, AvailabilityStore, ManifestFilter, ManifestStore,
+};
+use nodalync_types::Visibility;
+use nodalync_valid::Validator;
+use nodalync_wire::{
+    AvailabilityCheckPayload, AvailabilityResultPayload, PreviewRequestPayload,
+    MAX_AVAILABILITY_ADDRESSES,
+};
+use rand::seq::SliceRandom;
+use tracing::{debug, warn};
+
+use crate::error::{OpsError, OpsResult};
+use crate::events::OpsEvent;
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+/// How long a checker waits for each dial of the publisher.
+const CHECK_DIAL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a checker waits for the publisher's preview.
+const CHECK_PREVIEW_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Outcome of one availability check run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AvailabilityReport {
+    /// Updated record of each hash checked.
+    pub records: Vec<AvailabilityRecord>,
+}
+
+impl AvailabilityReport {
+    /// Number of hashes that were reachable.
+    pub fn reachable(&self) -> usize {
+        self.records.iter().filter(|r| r.last.reachable).count()
+    }
+
+    /// Number of hashes that were not.
+    pub fn unreachable(&self) -> usize {
+        self.records.len() - self.reachable()
+    }
+}
+
+/// A failed check's result.
+fn failed_result(hash: Hash, error: impl Into<String>) -> AvailabilityResultPayload {
+    AvailabilityResultPayload {
+        hash,
+        dialed: None,
+        previewed: false,
+        latency_ms: None,
+        error: Some(error.into()),
+    }
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Ask remote peers to fetch up to `sample_size` of our shared
+    /// content and record whether each was reachable.
+    ///
+    /// Content never checked goes first, then the least recently checked.
+    /// Returns an empty report when no checker is connected.
+    pub async fn check_availability(&self) -> OpsResult<AvailabilityReport> {
+        let network = self.network().cloned().ok_or_else(|| {
+            OpsError::invalid_operation("network required to check content availability")
+        })?;
+        let config = self.config.availability.clone();
+
+        let checkers = self.availability_checkers(&network);
+        if checkers.is_empty() {
+            debug!("No connected peer to check availability with");
+            return Ok(AvailabilityReport::default());
+        }
+
+        let shared = ManifestFilter::new()
+            .with_owner(self.peer_id())
+            .with_visibility(Visibility::Shared);
+        let mut sample = Vec::new();
+        for manifest in self.state.manifests.list(shared)? {
+            let last_checked = self
+                .state
+                .availability
+                .get(&manifest.hash)?
+                .map(|record| record.last.checked_at);
+            sample.push((manifest.hash, last_checked));
+        }
+        sample.sort_by_key(|(_, last_checked)| *last_checked);
+        sample.truncate(config.sample_size);
+
+        let addresses: Vec<String> = network
+            .listen_addresses()
+            .iter()
+            .take(MAX_AVAILABILITY_ADDRESSES)
+            .map(ToString::to_string)
+            .collect();
+        let timeout = Duration::from_millis(config.timeout_ms);
+
+        let mut report = AvailabilityReport::default();
+        for (hash, _) in sample {
+            let Some(&(libp2p_checker, checker)) = checkers.choose(&mut rand::thread_rng()) else {
+                break;
+            };
+            let request = AvailabilityCheckPayload {
+                hash,
+                addresses: addresses.clone(),
+            };
+            let result = match tokio::time::timeout(
+                timeout,
+                network.send_availability_check(libp2p_checker, request),
+            )
+            .await
+            {
+                Ok(Ok(result)) if result.hash == hash => result,
+                Ok(Ok(_)) => failed_result(hash, "checker answered for other content"),
+                Ok(Err(e)) => failed_result(hash, e.to_string()),
+                Err(_) => failed_result(hash, "availability check timed out"),
+            };
+
+            let record = self.state.availability.record_check(&AvailabilityCheck {
+                hash,
+                checker,
+                reachable: result.reachable(),
+                dialed: result.dialed.clone(),
+                latency_ms: result.latency_ms,
+                error: result.error.clone(),
+                checked_at: current_timestamp(),
+            })?;
+            debug!(
+                %hash,
+                checker = %checker,
+                reachable = record.last.reachable,
+                "Content availability checked"
+            );
+            if record.consecutive_failures == config.alert_after {
+                warn!(
+                    %hash,
+                    failures = record.consecutive_failures,
+                    error = record.last.error.as_deref().unwrap_or("not reachable"),
+                    "Shared content is unreachable"
+                );
+                self.emit(OpsEvent::ContentUnreachable {
+                    hash,
+                    checker,
+                    consecutive_failures: record.consecutive_failures,
+                    error: record.last.error.clone(),
+                });
+            }
+            report.records.push(record);
+        }
+
+        Ok(report)
+    }
+
+    /// Connected peers that can check our content: the configured checkers
+    /// that are connected, or every connected peer when none is.
+    fn availability_checkers(
+        &self,
+        network: &Arc<dyn Network>,
+    ) -> Vec<(nodalync_net::PeerId, PeerId)> {
+        let connected = network.connected_peers();
+        let configured: Vec<_> = self
+            .config
+            .availability
+            .checkers
+            .iter()
+            .filter_map(|peer| {
+                let libp2p_peer = network.libp2p_peer_id(peer)?;
+                connected
+                    .contains(&libp2p_peer)
+                    .then_some((libp2p_peer, *peer))
+            })
+            .collect();
+        if !configured.is_empty() {
+            return configured;
+        }
+        connected
+            .into_iter()
+            .filter_map(|libp2p_peer| Some((libp2p_peer, network.nodalync_peer_id(&libp2p_peer)?)))
+            .collect()
+    }
+
+    /// Check a peer's content for it: dial it at the first of its addresses
+    /// that answers and preview the content.
+    ///
+    /// Only content the requester owns is previewed. Failures are returned
+    /// in the result rather than as errors.
+    pub async fn handle_availability_check(
+        &self,
+        peer: &nodalync_net::PeerId,
+        requester: &PeerId,
+        request: &AvailabilityCheckPayload,
+    ) -> AvailabilityResultPayload {
+        let hash = request.hash;
+        if !self.config.availability.serve {
+            return failed_result(hash, "availability checks not served");
+        }
+        let Some(network) = self.network().cloned() else {
+            return failed_result(hash, "no network");
+        };
+
+        let mut dialed = None;
+        for address in request.addresses.iter().take(MAX_AVAILABILITY_ADDRESSES) {
+            let Ok(addr) = address.parse::<Multiaddr>() else {
+                continue;
+            };
+            match tokio::time::timeout(CHECK_DIAL_TIMEOUT, network.dial(addr)).await {
+                Ok(Ok(())) => {
+                    dialed = Some(address.clone());
+                    break;
+                }
+                Ok(Err(e)) => debug!(%address, error = %e, "Availability dial failed"),
+                Err(_) => debug!(%address, "Availability dial timed out"),
+            }
+        }
+
+        let sent = Instant::now();
+        let preview = tokio::time::timeout(
+            CHECK_PREVIEW_TIMEOUT,
+            network.send_preview_request(*peer, PreviewRequestPayload { hash }),
+        )
+        .await;
+        let latency_ms = sent.elapsed().as_millis().min(u32::MAX as u128) as u32;
+        let (previewed, error) = match preview {
+            Ok(Ok(response)) if response.manifest.owner == *requester => (true, None),
+            Ok(Ok(_)) => (false, Some("content not owned by requester".to_string())),
+            Ok(Err(e)) => (false, Some(e.to_string())),
+            Err(_) => (false, Some("preview timed out".to_string())),
+        };
+        let error = error.or_else(|| {
+            dialed
+                .is_none()
+                .then(|| "no address could be dialed".to_string())
+        });
+
+        AvailabilityResultPayload {
+            hash,
+            dialed,
+            previewed,
+            latency_ms: previewed.then_some(latency_ms),
+            error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AvailabilityConfig, OpsConfig};
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_test_utils::MockNetwork;
+    use nodalync_types::{L1Summary, Metadata};
+    use nodalync_wire::PreviewResponsePayload;
+    use tempfile::TempDir;
+
+    fn create_test_ops(config: AvailabilityConfig) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (_, public_key) = generate_identity();
+        let config = OpsConfig::default().with_availability(config);
+        let ops =
+            DefaultNodeOperations::with_config(state, peer_id_from_public_key(&public_key), config);
+        (ops, temp_dir)
+    }
+
+    async fn publish(ops: &mut DefaultNodeOperations, content: &[u8]) -> Hash {
+        let hash = ops
+            .create_content(content, Metadata::new("Notes", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 0)
+            .await
+            .unwrap();
+        hash
+    }
+
+    fn connected_peer(network: MockNetwork) -> (MockNetwork, nodalync_net::PeerId, PeerId) {
+        let libp2p_peer = nodalync_net::PeerId::random();
+        let (_, public_key) = generate_identity();
+        let peer = peer_id_from_public_key(&public_key);
+        let network = network
+            .with_connected_peer(libp2p_peer)
+            .with_peer_mapping(libp2p_peer, peer);
+        (network, libp2p_peer, peer)
+    }
+
+    fn reachable(hash: Hash) -> AvailabilityResultPayload {
+        AvailabilityResultPayload {
+            hash,
+            dialed: Some("/ip4/203.0.113.5/tcp/9000".to_string()),
+            previewed: true,
+            latency_ms: Some(80),
+            error: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_availability_records_results() {
+        let checker_peer = PeerId([9u8; 20]);
+        let (mut ops, _temp) = create_test_ops(
+            AvailabilityConfig::default()
+                .with_enabled(true)
+                .with_sample_size(2)
+                .with_alert_after(2),
+        );
+        let up = publish(&mut ops, b"reachable notes").await;
+        let down = publish(&mut ops, b"unreachable notes").await;
+        let later = publish(&mut ops, b"checked later").await;
+        ops.state
+            .availability
+            .record_check(&AvailabilityCheck {
+                hash: later,
+                checker: checker_peer,
+                reachable: true,
+                dialed: None,
+                latency_ms: None,
+                error: None,
+                checked_at: 1,
+            })
+            .unwrap();
+
+        let (network, libp2p_checker, checker) = connected_peer(MockNetwork::new());
+        let network = network
+            .with_listen_address("/ip4/203.0.113.5/tcp/9000".parse().unwrap())
+            .with_availability_result(reachable(up));
+        ops.set_network(Arc::new(network.clone()));
+        let mut events = ops.subscribe_events();
+
+        // Never-checked content goes first; `down` gets no result and times out
+        let report = ops.check_availability().await.unwrap();
+        assert_eq!((report.reachable(), report.unreachable()), (1, 1));
+        let checks = network.availability_checks();
+        assert_eq!(checks.len(), 2);
+        assert!(checks.iter().all(|(peer, _)| *peer == libp2p_checker));
+        assert_eq!(checks[0].1.addresses, vec!["/ip4/203.0.113.5/tcp/9000"]);
+        assert!(checks.iter().all(|(_, check)| check.hash != later));
+
+        let record = ops.state.availability.get(&up).unwrap().unwrap();
+        assert!(record.last.reachable);
+        assert_eq!(record.last.checker, checker);
+        assert_eq!(record.last.latency_ms, Some(80));
+        let record = ops.state.availability.get(&down).unwrap().unwrap();
+        assert_eq!(record.consecutive_failures, 1);
+        assert!(events.try_recv().is_err());
+
+        // The second failure in a row is reported, the first isn't
+        ops.config.availability = ops.config.availability.clone().with_sample_size(3);
+        let report = ops.check_availability().await.unwrap();
+        assert_eq!((report.reachable(), report.unreachable()), (1, 2));
+        let record = ops.state.availability.get(&later).unwrap().unwrap();
+        assert_eq!(record.consecutive_failures, 1);
+        match events.try_recv().unwrap() {
+            OpsEvent::ContentUnreachable {
+                hash,
+                consecutive_failures,
+                ..
+            } => {
+                assert_eq!(hash, down);
+                assert_eq!(consecutive_failures, 2);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_check_availability_prefers_configured_checkers() {
+        let network = MockNetwork::new();
+        let (network, _, _) = connected_peer(network);
+        let (network, libp2p_service, service) = connected_peer(network);
+        let (mut ops, _temp) =
+            create_test_ops(AvailabilityConfig::default().with_checkers(vec![service]));
+        let hash = publish(&mut ops, b"notes").await;
+        let network = network.with_availability_result(reachable(hash));
+        ops.set_network(Arc::new(network.clone()));
+
+        for _ in 0..4 {
+            ops.check_availability().await.unwrap();
+        }
+        assert!(network
+            .availability_checks()
+            .iter()
+            .all(|(peer, _)| *peer == libp2p_service));
+
+        // Nobody connected: nothing to check with
+        ops.set_network(Arc::new(MockNetwork::new()));
+        let report = ops.check_availability().await.unwrap();
+        assert!(report.records.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_handle_availability_check() {
+        let (mut ops, _temp) = create_test_ops(AvailabilityConfig::default());
+        let (_, public_key) = generate_identity();
+        let publisher = peer_id_from_public_key(&public_key);
+        let libp2p_publisher = nodalync_net::PeerId::random();
+
+        let hash = nodalync_crypto::content_hash(b"their notes");
+        let manifest =
+            nodalync_types::Manifest::new_l0(hash, publisher, Metadata::new("Notes", 11), 1);
+        let network = MockNetwork::new().with_preview_response(
+            hash,
+            PreviewResponsePayload {
+                hash,
+                manifest,
+                l1_summary: L1Summary::empty(hash),
+                collection: None,
+                forks: Vec::new(),
+                canonical: None,
+                window_queries: None,
+            },
+        );
+        ops.set_network(Arc::new(network.clone()));
+
+        let request = AvailabilityCheckPayload {
+            hash,
+            addresses: vec![
+                "not an address".to_string(),
+                "/ip4/203.0.113.5/tcp/9000".to_string(),
+            ],
+        };
+        let result = ops
+            .handle_availability_check(&libp2p_publisher, &publisher, &request)
+            .await;
+        assert!(result.reachable());
+        assert_eq!(result.dialed.as_deref(), Some("/ip4/203.0.113.5/tcp/9000"));
+        assert_eq!(network.dialed().len(), 1);
+
+        // Someone else's content isn't checked
+        let other = peer_id_from_public_key(&generate_identity().1);
+        let result = ops
+            .handle_availability_check(&libp2p_publisher, &other, &request)
+            .await;
+        assert!(!result.previewed);
+        assert!(!result.reachable());
+
+        // Nothing to dial
+        let result = ops
+            .handle_availability_check(
+                &libp2p_publisher,
+                &publisher,
+                &AvailabilityCheckPayload {
+                    hash,
+                    addresses: Vec::new(),
+                },
+            )
+            .await;
+        assert!(result.previewed);
+        assert!(!result.reachable());
+
+        ops.config.availability = AvailabilityConfig::default().with_serve(false);
+        let result = ops
+            .handle_availability_check(&libp2p_publisher, &publisher, &request)
+            .await;
+        assert!(!result.reachable());
+        assert!(result.error.is_some());
+    }
+}
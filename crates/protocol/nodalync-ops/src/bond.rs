@@ -0,0 +1,417 @@
+//! Publisher bonds.
+//!
+//! A publisher stakes part of its settlement balance as a bond
+//! ([`post_bond`](NodeOperations::post_bond)), and the bond it holds is
+//! recorded in the `economics.bond` of the manifests it publishes. That
+//! makes flooding the network from many cheap identities costly.
+//!
+//! Before paying for content, a node checks the publisher's claim against
+//! [`BondConfig::requirements`](crate::BondConfig::requirements) for the
+//! content's visibility. With a settlement layer the claim must also be
+//! backed by the publisher's bond on-chain; looked-up bonds are cached for
+//! [`BondConfig::cache_ttl_ms`](crate::BondConfig::cache_ttl_ms). A
+//! publisher without a known settlement account has no bond we can see.
+//!
+//! A publisher that serves content not matching the requested hash is
+//! caught in the act. With
+//! [`BondConfig::slash_on_fraud`](crate::BondConfig::slash_on_fraud), its
+//! bond is slashed, with a fraud proof as evidence where there is one (see
+//! [`crate::fraud`]).
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use nodalync_crypto::{Hash, PeerId};
+use nodalync_settle::{SettleError, TransactionId};
+use nodalync_store::{ManifestFilter, ManifestStore, WalletTransaction, WalletTransactionKind};
+use nodalync_types::{Amount, Manifest, PublisherBond, Visibility};
+use nodalync_valid::{validate_publisher_bond, BondChecker, Validator};
+use tracing::{info, warn};
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, lock, NodeOperations};
+
+/// Cached on-chain bonds of other publishers.
+#[derive(Debug, Default)]
+pub(crate) struct BondCache {
+    entries: HashMap<PeerId, (Amount, Instant)>,
+}
+
+impl BondCache {
+    fn get(&self, peer: &PeerId, ttl: Duration) -> Option<Amount> {
+        self.entries
+            .get(peer)
+            .filter(|(_, checked_at)| checked_at.elapsed() < ttl)
+            .map(|(amount, _)| *amount)
+    }
+
+    fn insert(&mut self, peer: PeerId, amount: Amount) {
+        self.entries.insert(peer, (amount, Instant::now()));
+    }
+
+    fn remove(&mut self, peer: &PeerId) {
+        self.entries.remove(peer);
+    }
+}
+
+/// A publisher's bond as posted on-chain.
+struct PostedBond {
+    publisher: PeerId,
+    amount: Amount,
+}
+
+impl BondChecker for PostedBond {
+    fn has_bond(&self, peer_id: &PeerId, amount: u64) -> bool {
+        *peer_id == self.publisher && self.amount >= amount
+    }
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Bond `amount` from our settlement contract balance.
+    ///
+    /// Records the transaction in the local wallet history, and the new
+    /// total bond on the manifests we have published. Content published
+    /// later records it too.
+    pub async fn post_bond(&self, amount: Amount) -> OpsResult<PublisherBond> {
+        if amount == 0 {
+            return Err(OpsError::invalid_operation("bond amount must be > 0"));
+        }
+        let settlement = self
+            .settlement()
+            .cloned()
+            .ok_or(OpsError::SettlementRequired)?;
+
+        let tx_id = settlement
+            .post_bond(amount)
+            .await
+            .map_err(|e| OpsError::SettlementFailed(e.to_string()))?
+            .to_string();
+        info!(amount, tx_id = %tx_id, "Posted publisher bond");
+
+        self.state
+            .settlement
+            .record_transaction(&WalletTransaction::new(
+                tx_id.clone(),
+                WalletTransactionKind::Bond,
+                amount,
+                current_timestamp(),
+            ))?;
+
+        let total = settlement
+            .get_bond(&settlement.get_own_account())
+            .await
+            .map_err(|e| OpsError::SettlementFailed(e.to_string()))?;
+        let bond = PublisherBond::new(total, tx_id);
+
+        let manifests = self.state.manifests.list(ManifestFilter::new())?;
+        for mut manifest in manifests {
+            let published = matches!(
+                manifest.visibility,
+                Visibility::Shared | Visibility::Unlisted
+            );
+            if manifest.owner == self.peer_id() && published {
+                manifest.economics.bond = Some(bond.clone());
+                self.state.manifests.update(&manifest)?;
+            }
+        }
+
+        Ok(bond)
+    }
+
+    /// Get our bond as posted on-chain.
+    ///
+    /// `None` without a settlement layer or a bond.
+    pub async fn own_bond(&self) -> OpsResult<Option<PublisherBond>> {
+        let Some(settlement) = self.settlement().cloned() else {
+            return Ok(None);
+        };
+        let total = settlement
+            .get_bond(&settlement.get_own_account())
+            .await
+            .map_err(|e| OpsError::SettlementFailed(e.to_string()))?;
+        if total == 0 {
+            return Ok(None);
+        }
+
+        let tx_id = self
+            .state
+            .settlement
+            .latest_transaction(WalletTransactionKind::Bond)?
+            .map(|tx| tx.transaction_id)
+            .unwrap_or_default();
+        Ok(Some(PublisherBond::new(total, tx_id)))
+    }
+
+    /// Record our current bond in a manifest being published.
+    ///
+    /// A bond that can't be looked up leaves the manifest as it is.
+    pub(crate) async fn record_bond(&self, manifest: &mut Manifest) {
+        match self.own_bond().await {
+            Ok(Some(bond)) => manifest.economics.bond = Some(bond),
+            Ok(None) if self.has_settlement() => manifest.economics.bond = None,
+            Ok(None) => {}
+            Err(e) => warn!(hash = %manifest.hash, error = %e, "Failed to look up our bond"),
+        }
+    }
+
+    /// Check a publisher's bond before paying for its content.
+    ///
+    /// # Errors
+    /// - `Validation(PublisherBondRequired)` if the manifest claims less than
+    ///   its visibility requires
+    /// - `Validation(PublisherBondUnbacked)` if the claim isn't posted
+    ///   on-chain
+    /// - `SettlementFailed` if the bond can't be looked up
+    pub(crate) async fn check_publisher_bond(&self, manifest: &Manifest) -> OpsResult<()> {
+        let requirements = self.config.bonds.requirements;
+        if manifest.owner == self.peer_id() || requirements.required_for(manifest.visibility) == 0 {
+            return Ok(());
+        }
+
+        let posted = self
+            .lookup_bond(&manifest.owner)
+            .await?
+            .map(|amount| PostedBond {
+                publisher: manifest.owner,
+                amount,
+            });
+        validate_publisher_bond(
+            manifest,
+            &requirements,
+            posted.as_ref().map(|b| b as &dyn BondChecker),
+        )
+        .inspect_err(|e| warn!(hash = %manifest.hash, "Publisher bond rejected: {}", e))?;
+        Ok(())
+    }
+
+    /// Look up a publisher's bond on-chain, through the cache.
+    ///
+    /// `None` without a settlement layer.
+    async fn lookup_bond(&self, publisher: &PeerId) -> OpsResult<Option<Amount>> {
+        let Some(settlement) = self.settlement().cloned() else {
+            return Ok(None);
+        };
+        let ttl = Duration::from_millis(self.config.bonds.cache_ttl_ms);
+        let cached = lock(&self.bond_cache).get(publisher, ttl);
+        if let Some(amount) = cached {
+            return Ok(Some(amount));
+        }
+
+        let amount = match settlement.get_account_for_peer(publisher) {
+            Some(account) => settlement
+                .get_bond(&account)
+                .await
+                .map_err(|e| OpsError::SettlementFailed(e.to_string()))?,
+            None => 0,
+        };
+        lock(&self.bond_cache).insert(*publisher, amount);
+        Ok(Some(amount))
+    }
+
+    /// Slash a publisher's whole bond on proven fraud.
+    ///
+    /// `evidence` identifies the proof, e.g. the hash of the bad content
+    /// the publisher served. Returns `None` if it has no bond to slash.
+    pub async fn slash_publisher_bond(
+        &self,
+        publisher: &PeerId,
+        evidence: &Hash,
+    ) -> OpsResult<Option<TransactionId>> {
+        let settlement = self
+            .settlement()
+            .cloned()
+            .ok_or(OpsError::SettlementRequired)?;
+        let account = settlement
+            .get_account_for_peer(publisher)
+            .ok_or_else(|| SettleError::account_not_found(publisher.to_string()))
+            .map_err(|e| OpsError::SettlementFailed(e.to_string()))?;
+
+        let bond = settlement
+            .get_bond(&account)
+            .await
+            .map_err(|e| OpsError::SettlementFailed(e.to_string()))?;
+        if bond == 0 {
+            return Ok(None);
+        }
+        let tx_id = settlement
+            .slash_bond(&account, bond, evidence)
+            .await
+            .map_err(|e| OpsError::SettlementFailed(e.to_string()))?;
+        lock(&self.bond_cache).remove(publisher);
+
+        warn!(
+            publisher = %publisher,
+            amount = bond,
+            evidence = %evidence,
+            tx_id = %tx_id,
+            "Slashed publisher bond"
+        );
+        Ok(Some(tx_id))
+    }
+
+    /// Slash the bond of a publisher caught serving bad content, if the
+    /// bond config says to.
+    ///
+    /// Failures are logged, as the caller goes on either way.
+    pub(crate) async fn slash_for_fraud(&self, publisher: &PeerId, evidence: &Hash) {
+        if !self.config.bonds.slash_on_fraud || !self.has_settlement() {
+            return;
+        }
+        if let Err(e) = self.slash_publisher_bond(publisher, evidence).await {
+            warn!(publisher = %publisher, error = %e, "Failed to slash publisher bond");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{BondConfig, OpsConfig};
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_settle::{AccountId, Settlement};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_test_utils::MockSettlement;
+    use nodalync_types::Metadata;
+    use nodalync_valid::{BondRequirements, ValidationError};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn create_test_ops(config: OpsConfig) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (_, public_key) = generate_identity();
+        let ops =
+            DefaultNodeOperations::with_config(state, peer_id_from_public_key(&public_key), config);
+        (ops, temp_dir)
+    }
+
+    fn requiring(shared: Amount) -> OpsConfig {
+        OpsConfig::default().with_bonds(
+            BondConfig::default()
+                .with_requirements(BondRequirements::new().with_shared(shared))
+                .with_slash_on_fraud(true),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_post_bond_records_on_manifests() {
+        let (mut publisher, _temp) = create_test_ops(OpsConfig::default());
+        let settlement = Arc::new(MockSettlement::new().with_balance(10_000));
+        publisher.set_settlement(settlement.clone());
+
+        let content = b"Bonded notes";
+        let early = publisher
+            .create_content(content, Metadata::new("Early", content.len() as u64))
+            .unwrap();
+        publisher
+            .publish_content(&early, Visibility::Shared, 100)
+            .await
+            .unwrap();
+        let manifest = publisher.get_content_manifest(&early).unwrap().unwrap();
+        assert!(manifest.economics.bond.is_none());
+
+        assert!(publisher.post_bond(0).await.is_err());
+        let bond = publisher.post_bond(4_000).await.unwrap();
+        assert_eq!(bond.amount, 4_000);
+        assert_eq!(settlement.current_balance(), 6_000);
+        let recent = publisher.recent_wallet_transactions(1).unwrap();
+        assert_eq!(recent[0].kind, WalletTransactionKind::Bond);
+
+        // Already published content records the bond
+        let manifest = publisher.get_content_manifest(&early).unwrap().unwrap();
+        assert_eq!(manifest.economics.bond, Some(bond.clone()));
+
+        // And so does content published later
+        let late = publisher
+            .create_content(b"More notes", Metadata::new("Late", 10))
+            .unwrap();
+        publisher
+            .publish_content(&late, Visibility::Shared, 100)
+            .await
+            .unwrap();
+        let manifest = publisher.get_content_manifest(&late).unwrap().unwrap();
+        assert_eq!(manifest.economics.bond, Some(bond));
+    }
+
+    #[tokio::test]
+    async fn test_check_publisher_bond() {
+        let (mut reader, _temp) = create_test_ops(requiring(1_000));
+        let (_, public_key) = generate_identity();
+        let publisher = peer_id_from_public_key(&public_key);
+
+        let mut manifest = Manifest::new_l0(
+            content_hash(b"content"),
+            publisher,
+            Metadata::new("Notes", 7),
+            current_timestamp(),
+        );
+        manifest.visibility = Visibility::Shared;
+
+        // No claim
+        assert!(matches!(
+            reader.check_publisher_bond(&manifest).await,
+            Err(OpsError::Validation(
+                ValidationError::PublisherBondRequired { claimed: 0, .. }
+            ))
+        ));
+
+        // Without a settlement layer a sufficient claim is taken as it is
+        manifest.economics.bond = Some(PublisherBond::new(1_000, "0.0.7@1.1"));
+        reader.check_publisher_bond(&manifest).await.unwrap();
+
+        // With one, the chain has to back it
+        let settlement = Arc::new(MockSettlement::new());
+        reader.set_settlement(settlement.clone());
+        assert!(matches!(
+            reader.check_publisher_bond(&manifest).await,
+            Err(OpsError::Validation(
+                ValidationError::PublisherBondUnbacked { required: 1_000 }
+            ))
+        ));
+
+        settlement.register_peer_account(&publisher, AccountId::simple(7));
+        settlement.set_bond(AccountId::simple(7), 1_000);
+        reader.config.bonds.cache_ttl_ms = 0;
+        reader.check_publisher_bond(&manifest).await.unwrap();
+
+        // Private content needs no bond
+        manifest.visibility = Visibility::Private;
+        manifest.economics.bond = None;
+        reader.check_publisher_bond(&manifest).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_slash_for_fraud() {
+        let (mut reader, _temp) = create_test_ops(requiring(1_000));
+        let (_, public_key) = generate_identity();
+        let publisher = peer_id_from_public_key(&public_key);
+        let settlement = Arc::new(MockSettlement::new());
+        reader.set_settlement(settlement.clone());
+        settlement.register_peer_account(&publisher, AccountId::simple(7));
+        settlement.set_bond(AccountId::simple(7), 1_500);
+
+        let evidence = content_hash(b"not what was asked for");
+        reader.slash_for_fraud(&publisher, &evidence).await;
+        assert_eq!(
+            settlement.slashes(),
+            vec![(AccountId::simple(7), 1_500, evidence)]
+        );
+        assert_eq!(settlement.get_bond(&AccountId::simple(7)).await.unwrap(), 0);
+
+        // Nothing left to slash
+        let result = reader.slash_publisher_bond(&publisher, &evidence).await;
+        assert!(matches!(result, Ok(None)));
+
+        // Not slashed unless configured to
+        settlement.set_bond(AccountId::simple(7), 1_500);
+        reader.config.bonds.slash_on_fraud = false;
+        reader.slash_for_fraud(&publisher, &evidence).await;
+        assert_eq!(settlement.slashes().len(), 1);
+    }
+}
@@ -0,0 +1,269 @@
+//! Capability tokens for sharing unpublished content.
+//!
+//! An owner mints a token for one of its content hashes, optionally bound
+//! to a single peer, and hands it over out of band. The holder queries the
+//! owner directly with the token attached; the query handler accepts a
+//! valid token in place of the visibility and access-list checks, so
+//! private content can be shared without being published. Payment rules
+//! are unchanged.
+
+use nodalync_crypto::{Hash, PeerId};
+use nodalync_store::ManifestStore;
+use nodalync_types::{Amount, CapabilityToken, ContentType, DEFAULT_CAPABILITY_EXPIRY_MS};
+use nodalync_valid::{sign_capability, ValidationError, Validator};
+use tracing::info;
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+use crate::ops::QueryResponse;
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Mint a capability token for one of our content hashes.
+    ///
+    /// The token expires `expires_in` milliseconds from now (default
+    /// [`DEFAULT_CAPABILITY_EXPIRY_MS`]) and, with a `grantee`, can only be
+    /// used by that peer. L2 content can never be shared. Requires the
+    /// private key for signing.
+    pub fn mint_capability(
+        &self,
+        hash: &Hash,
+        grantee: Option<PeerId>,
+        expires_in: Option<u64>,
+    ) -> OpsResult<CapabilityToken> {
+        let manifest = self
+            .state
+            .manifests
+            .load(hash)?
+            .ok_or(OpsError::ManifestNotFound(*hash))?;
+        if manifest.content_type == ContentType::L2 {
+            return Err(OpsError::Validation(ValidationError::L2CannotPublish));
+        }
+        if manifest.owner != self.peer_id() {
+            return Err(OpsError::AccessDenied);
+        }
+
+        let private_key = self.private_key().ok_or(OpsError::PrivateKeyRequired)?;
+        let issued_at = current_timestamp();
+        let expires_at =
+            issued_at.saturating_add(expires_in.unwrap_or(DEFAULT_CAPABILITY_EXPIRY_MS));
+
+        let mut token = CapabilityToken::new(
+            *hash,
+            self.peer_id(),
+            private_key.public_key(),
+            grantee,
+            issued_at,
+            expires_at,
+        );
+        sign_capability(private_key, &mut token);
+
+        info!(hash = %hash, grantee = ?grantee, expires_at, "Minted capability token");
+        Ok(token)
+    }
+
+    /// Query content shared with us through a capability token.
+    ///
+    /// The query goes straight to the token's issuer, since shared content
+    /// need not be announced. Paid content still needs `payment_amount` to
+    /// cover the price and an open channel with the issuer.
+    pub async fn query_with_capability(
+        &self,
+        token: &CapabilityToken,
+        payment_amount: Amount,
+    ) -> OpsResult<QueryResponse> {
+        if token.is_expired(current_timestamp()) {
+            return Err(OpsError::Validation(ValidationError::CapabilityExpired {
+                expires_at: token.expires_at,
+            }));
+        }
+        if !token.allows(&self.peer_id()) {
+            return Err(OpsError::AccessDenied);
+        }
+
+        let network = self.network().cloned().ok_or_else(|| {
+            OpsError::invalid_operation("network required to query by capability")
+        })?;
+
+        self.fetch_content_from_network(
+            &token.content_hash,
+            &token.issuer,
+            payment_amount,
+            &network,
+            Some(token.clone()),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OpsConfig;
+    use crate::DefaultNodeOperations;
+    use nodalync_crypto::{
+        content_hash, decrypt_content, generate_identity, peer_id_from_public_key,
+        unwrap_content_key,
+    };
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_types::Metadata;
+    use nodalync_wire::QueryRequestPayload;
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops = DefaultNodeOperations::with_config(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default(),
+        );
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    fn request(token: Option<CapabilityToken>, hash: Hash) -> QueryRequestPayload {
+        QueryRequestPayload {
+            hash,
+            query: None,
+            payment: None,
+            version_spec: None,
+            payment_nonce: 0,
+            capability: token,
+            // Restricted content is only delivered encrypted
+            recipient_key: Some(generate_identity().1),
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        }
+    }
+
+    #[test]
+    fn test_mint_capability() {
+        let (ops, _temp) = create_test_ops();
+        let content = b"Unpublished notes";
+        let hash = ops
+            .create_content(content, Metadata::new("Notes", content.len() as u64))
+            .unwrap();
+        let grantee = test_peer_id();
+
+        let token = ops
+            .mint_capability(&hash, Some(grantee), Some(60_000))
+            .unwrap();
+        assert_eq!(token.content_hash, hash);
+        assert_eq!(token.issuer, ops.peer_id());
+        assert_eq!(token.expires_at - token.issued_at, 60_000);
+
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        ops.validator
+            .validate_capability(&token, &grantee, &manifest)
+            .unwrap();
+
+        // Unknown content cannot be shared
+        assert!(matches!(
+            ops.mint_capability(&content_hash(b"unknown"), None, None),
+            Err(OpsError::ManifestNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_mint_capability_requires_ownership() {
+        let (owner, _temp) = create_test_ops();
+        let content = b"Someone else's notes";
+        let hash = owner
+            .create_content(content, Metadata::new("Notes", content.len() as u64))
+            .unwrap();
+        let manifest = owner.get_content_manifest(&hash).unwrap().unwrap();
+
+        let (other, _temp2) = create_test_ops();
+        other.state.manifests.store(&manifest).unwrap();
+        assert!(matches!(
+            other.mint_capability(&hash, None, None),
+            Err(OpsError::AccessDenied)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_query_private_content_with_capability() {
+        let (ops, _temp) = create_test_ops();
+        let content = b"Private content shared by token";
+        let hash = ops
+            .create_content(content, Metadata::new("Private", content.len() as u64))
+            .unwrap();
+        let (grantee_key, grantee_public) = generate_identity();
+        let grantee = peer_id_from_public_key(&grantee_public);
+
+        // Private content is refused without a token
+        assert!(ops
+            .handle_query_request(&grantee, &request(None, hash))
+            .await
+            .is_err());
+
+        let token = ops.mint_capability(&hash, Some(grantee), None).unwrap();
+        let mut query = request(Some(token.clone()), hash);
+        query.recipient_key = Some(grantee_public);
+        let response = ops.handle_query_request(&grantee, &query).await.unwrap();
+
+        // Private content is delivered encrypted to the grantee
+        let key = unwrap_content_key(&response.content_key.unwrap(), &grantee_key).unwrap();
+        assert_eq!(
+            decrypt_content(&key, &response.content).unwrap(),
+            content.to_vec()
+        );
+
+        // Bound to the grantee
+        assert!(ops
+            .handle_query_request(&test_peer_id(), &request(Some(token.clone()), hash))
+            .await
+            .is_err());
+
+        // Only valid for the content it was minted for
+        let other = b"Other private content";
+        let other_hash = ops
+            .create_content(other, Metadata::new("Other", other.len() as u64))
+            .unwrap();
+        assert!(ops
+            .handle_query_request(&grantee, &request(Some(token), other_hash))
+            .await
+            .is_err());
+
+        // Expired tokens are refused
+        let expired = ops.mint_capability(&hash, Some(grantee), Some(0)).unwrap();
+        assert!(matches!(
+            ops.handle_query_request(&grantee, &request(Some(expired), hash))
+                .await,
+            Err(OpsError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_query_with_capability_checks_token() {
+        let (owner, _temp) = create_test_ops();
+        let content = b"Shared with someone else";
+        let hash = owner
+            .create_content(content, Metadata::new("Shared", content.len() as u64))
+            .unwrap();
+        let token = owner
+            .mint_capability(&hash, Some(test_peer_id()), None)
+            .unwrap();
+
+        let (holder, _temp2) = create_test_ops();
+        assert!(matches!(
+            holder.query_with_capability(&token, 0).await,
+            Err(OpsError::AccessDenied)
+        ));
+    }
+}
@@ -0,0 +1,292 @@
+//! Proof-of-possession challenges for paid queries.
+//!
+//! With [`OpsConfig::query_challenge`](crate::OpsConfig::query_challenge)
+//! set, a paid query for content at or above the configured price is first
+//! answered with a [`QueryChallenge`] instead of the content. The requester
+//! signs the challenge with the identity key its payment channel belongs to
+//! and sends the query again with the answer. Each challenge is good for
+//! one answer and expires after `ttl_ms`, so a query request captured on
+//! the wire can't be replayed by a third party.
+//!
+//! On the requesting side, queries answer a challenge automatically when
+//! a private key is loaded.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use nodalync_crypto::{Hash, PeerId};
+use nodalync_net::{Network, NetworkError, NetworkResult};
+use nodalync_types::{Manifest, Timestamp};
+use nodalync_valid::{sign_challenge, validate_challenge_response, Validator};
+use nodalync_wire::{QueryChallenge, QueryRequestPayload, QueryResponsePayload};
+use rand::RngCore;
+use tracing::debug;
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, lock, NodeOperations};
+
+/// Challenges issued and not yet answered, by requester and content.
+#[derive(Debug, Default)]
+pub(crate) struct ChallengeTracker {
+    pending: HashMap<(PeerId, Hash), QueryChallenge>,
+}
+
+impl ChallengeTracker {
+    /// Issue a fresh challenge, replacing any earlier one for the same
+    /// requester and content.
+    fn issue(
+        &mut self,
+        requester: PeerId,
+        content: Hash,
+        provider: PeerId,
+        now: Timestamp,
+        ttl_ms: u64,
+    ) -> QueryChallenge {
+        self.pending.retain(|_, c| c.expires_at >= now);
+
+        let mut nonce = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let challenge = QueryChallenge {
+            nonce: Hash(nonce),
+            provider,
+            expires_at: now.saturating_add(ttl_ms),
+        };
+        self.pending.insert((requester, content), challenge.clone());
+        challenge
+    }
+
+    /// Remove the unexpired challenge issued for a query, if any.
+    fn take(&mut self, requester: PeerId, content: Hash, now: Timestamp) -> Option<QueryChallenge> {
+        self.pending
+            .remove(&(requester, content))
+            .filter(|c| c.expires_at >= now)
+    }
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Require a paid query for challenged content to answer a challenge.
+    ///
+    /// A query without an answer, or answering a challenge that is unknown,
+    /// expired or already used, gets a new challenge as
+    /// `ChallengeRequired`. An answer is checked against the outstanding
+    /// challenge, which is consumed either way.
+    pub(crate) fn ensure_challenge_answered(
+        &self,
+        requester: &PeerId,
+        request: &QueryRequestPayload,
+        manifest: &Manifest,
+    ) -> OpsResult<()> {
+        if !self
+            .config
+            .query_challenge
+            .applies_to(manifest.economics.price)
+        {
+            return Ok(());
+        }
+
+        let now = current_timestamp();
+        let outstanding = lock(&self.query_challenges).take(*requester, request.hash, now);
+        match (&request.challenge_response, outstanding) {
+            (Some(response), Some(challenge)) if response.nonce == challenge.nonce => {
+                validate_challenge_response(response, &challenge, &request.hash, requester, now)?;
+                Ok(())
+            }
+            _ => {
+                let challenge = lock(&self.query_challenges).issue(
+                    *requester,
+                    request.hash,
+                    self.peer_id(),
+                    now,
+                    self.config.query_challenge.ttl_ms,
+                );
+                debug!(requester = %requester, hash = %request.hash, "Issued query challenge");
+                Err(OpsError::ChallengeRequired(challenge))
+            }
+        }
+    }
+
+    /// Send a query, answering the provider's challenge if it issues one.
+    ///
+    /// Without a private key the challenge can't be answered and is
+    /// returned as the error.
+    pub(crate) async fn send_query_answering_challenge(
+        &self,
+        network: &Arc<dyn Network>,
+        peer: nodalync_net::PeerId,
+        mut request: QueryRequestPayload,
+    ) -> NetworkResult<QueryResponsePayload> {
+        match self
+            .send_query_retrying(network, peer, request.clone())
+            .await
+        {
+            Err(NetworkError::ChallengeRequired { challenge }) => {
+                let Some(private_key) = self.private_key() else {
+                    return Err(NetworkError::ChallengeRequired { challenge });
+                };
+                debug!(peer = %peer, hash = %request.hash, "Answering query challenge");
+                request.challenge_response =
+                    Some(sign_challenge(private_key, &challenge, &request.hash));
+                self.send_query_retrying(network, peer, request).await
+            }
+            result => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{OpsConfig, QueryChallengeConfig};
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key, Signature};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_test_utils::MockSettlement;
+    use nodalync_types::{Metadata, Payment, Visibility};
+    use nodalync_valid::ValidationError;
+    use tempfile::TempDir;
+
+    const PRICE: u64 = 1_000;
+
+    fn create_challenging_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (_, public_key) = generate_identity();
+        let config = OpsConfig::default()
+            .with_query_challenge(QueryChallengeConfig::default().with_min_price(PRICE));
+        let ops = DefaultNodeOperations::with_config_and_settlement(
+            state,
+            peer_id_from_public_key(&public_key),
+            config,
+            Arc::new(MockSettlement::new()),
+        );
+        (ops, temp_dir)
+    }
+
+    fn paid_request(
+        ops: &DefaultNodeOperations,
+        hash: Hash,
+        channel_id: Hash,
+    ) -> QueryRequestPayload {
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        QueryRequestPayload {
+            hash,
+            query: None,
+            payment: Some(Payment::new(
+                content_hash(b"payment"),
+                channel_id,
+                PRICE,
+                manifest.owner,
+                hash,
+                manifest.provenance.root_l0l1.clone(),
+                current_timestamp(),
+                Signature::from_bytes([0u8; 64]),
+            )),
+            version_spec: None,
+            payment_nonce: 1,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        }
+    }
+
+    fn issued(result: OpsResult<QueryResponsePayload>) -> QueryChallenge {
+        match result {
+            Err(OpsError::ChallengeRequired(challenge)) => challenge,
+            other => panic!("expected a challenge, got {:?}", other.map(|r| r.hash)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_paid_query_served_after_challenge_answered() {
+        let (ops, _dir) = create_challenging_ops();
+        let content = b"Expensive content";
+        let hash = ops
+            .create_content(content, Metadata::new("Expensive", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, PRICE)
+            .await
+            .unwrap();
+
+        let (requester_key, requester_public) = generate_identity();
+        let requester = peer_id_from_public_key(&requester_public);
+        let channel_id = content_hash(b"challenge-channel");
+        ops.accept_payment_channel(&channel_id, &requester, 5_000, 1_000)
+            .unwrap();
+
+        // First attempt is challenged
+        let mut request = paid_request(&ops, hash, channel_id);
+        let challenge = issued(ops.handle_query_request(&requester, &request).await);
+        assert_eq!(challenge.provider, ops.peer_id());
+
+        // Answering it gets the content
+        request.challenge_response = Some(sign_challenge(&requester_key, &challenge, &hash));
+        let response = ops
+            .handle_query_request(&requester, &request)
+            .await
+            .unwrap();
+        assert_eq!(&*response.content, content);
+
+        // Replaying the answered request only earns a fresh challenge
+        let fresh = issued(ops.handle_query_request(&requester, &request).await);
+        assert_ne!(fresh.nonce, challenge.nonce);
+    }
+
+    #[tokio::test]
+    async fn test_challenge_answered_by_other_key_rejected() {
+        let (ops, _dir) = create_challenging_ops();
+        let content = b"Expensive content";
+        let hash = ops
+            .create_content(content, Metadata::new("Expensive", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, PRICE)
+            .await
+            .unwrap();
+
+        let (_, requester_public) = generate_identity();
+        let requester = peer_id_from_public_key(&requester_public);
+        let channel_id = content_hash(b"challenge-channel");
+        ops.accept_payment_channel(&channel_id, &requester, 5_000, 1_000)
+            .unwrap();
+
+        let mut request = paid_request(&ops, hash, channel_id);
+        let challenge = issued(ops.handle_query_request(&requester, &request).await);
+
+        // A third party can't answer for the channel owner
+        let (eavesdropper, _) = generate_identity();
+        request.challenge_response = Some(sign_challenge(&eavesdropper, &challenge, &hash));
+        let result = ops.handle_query_request(&requester, &request).await;
+        assert!(matches!(
+            result,
+            Err(OpsError::Validation(
+                ValidationError::InvalidChallengeResponse { .. }
+            ))
+        ));
+
+        // and the challenge is spent
+        issued(ops.handle_query_request(&requester, &request).await);
+    }
+
+    #[test]
+    fn test_challenge_applies_to_price() {
+        let config = QueryChallengeConfig::default();
+        assert!(!config.applies_to(PRICE));
+
+        let config = config.with_min_price(PRICE);
+        assert!(config.applies_to(PRICE));
+        assert!(!config.applies_to(PRICE - 1));
+        assert!(!config.applies_to(0));
+        assert!(QueryChallengeConfig::default()
+            .with_min_price(0)
+            .applies_to(1));
+    }
+}
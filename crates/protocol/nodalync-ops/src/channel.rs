@@ -2,22 +2,91 @@
 //!
 //! This module implements payment channel operations as specified
 //! in Protocol Specification §7.3.
+//!
+//! Payments load a channel, change it and write it back. The sequence runs
+//! under a per-peer lock ([`ChannelLocks`]), so concurrent payments on one
+//! channel don't lose updates while payments with other peers, and reads,
+//! go ahead. The lock is never held across an `.await`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 use nodalync_crypto::{content_hash, sign, Hash, PeerId, PrivateKey, Signature};
-use nodalync_store::ChannelStore;
+use nodalync_net::Network;
+use nodalync_store::{
+    ChannelCheckpoint, ChannelStore, DisputeRecord, DisputeStore, LedgerAccount, LedgerEvent,
+    PaymentDirection, PeerStore,
+};
 use nodalync_types::{
     Amount, Channel, Manifest, Payment, PendingClose, PendingDispute, ProvenanceEntry,
 };
-use nodalync_valid::{construct_payment_message, sign_channel_close, Validator};
+use nodalync_valid::{
+    construct_payment_message, sign_channel_close, verify_channel_close_signature, Validator,
+};
 use nodalync_wire::{
     ChannelBalances, ChannelCloseAckPayload, ChannelClosePayload, ChannelOpenPayload,
-    ChannelUpdatePayload,
+    ChannelSyncPayload,
 };
 use rand::Rng;
 
+use crate::config::AutoOpenRequest;
+use crate::dispute::signed_channel_state;
 use crate::error::{OpsError, OpsResult};
+use crate::events::OpsEvent;
 use crate::extraction::L1Extractor;
-use crate::node_ops::{current_timestamp, NodeOperations};
+use crate::node_ops::{current_timestamp, lock, NodeOperations};
+
+/// Outcome of the startup recovery pass for one channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelRecovery {
+    /// Channel counterparty.
+    pub peer: PeerId,
+    /// Channel identifier.
+    pub channel_id: Hash,
+    /// Nonce of the stored channel before recovery.
+    pub stored_nonce: u64,
+    /// Highest checkpointed nonce.
+    pub checkpoint_nonce: u64,
+    /// Whether the channel was rolled forward to the latest checkpoint.
+    pub restored: bool,
+    /// Inclusive nonce ranges with no local checkpoint.
+    pub gaps: Vec<(u64, u64)>,
+}
+
+impl ChannelRecovery {
+    /// Whether the channel should be resynced with the counterparty.
+    pub fn needs_resync(&self) -> bool {
+        !self.gaps.is_empty()
+    }
+}
+
+/// A channel whose nonce was corrected by `reconcile_payment_nonces`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonceReconciliation {
+    /// Channel counterparty.
+    pub peer: PeerId,
+    /// Channel identifier.
+    pub channel_id: Hash,
+    /// Nonce of the stored channel before reconciliation.
+    pub stored_nonce: u64,
+    /// Highest nonce tracked for payments we signed.
+    pub last_sent: u64,
+    /// Highest nonce tracked for payments we accepted.
+    pub last_received: u64,
+    /// Nonce the channel was moved to.
+    pub recovered_nonce: u64,
+}
+
+/// Per-peer locks serializing updates to channel state.
+#[derive(Debug, Default)]
+pub(crate) struct ChannelLocks(Mutex<HashMap<PeerId, Arc<Mutex<()>>>>);
+
+impl ChannelLocks {
+    /// Get the lock for the channel with `peer`.
+    pub(crate) fn for_peer(&self, peer: &PeerId) -> Arc<Mutex<()>> {
+        Arc::clone(lock(&self.0).entry(*peer).or_default())
+    }
+}
 
 impl<V, E> NodeOperations<V, E>
 where
@@ -44,11 +113,8 @@ where
     ///
     /// Returns the channel. If settlement is configured, the channel will have
     /// a `funding_tx_id` with the on-chain transaction ID.
-    pub async fn open_payment_channel(
-        &mut self,
-        peer: &PeerId,
-        deposit: Amount,
-    ) -> OpsResult<Channel> {
+    #[tracing::instrument(name = "open_channel", skip(self), fields(%peer, amount = deposit))]
+    pub async fn open_payment_channel(&self, peer: &PeerId, deposit: Amount) -> OpsResult<Channel> {
         let timestamp = current_timestamp();
 
         // Check if channel already exists
@@ -106,6 +172,7 @@ where
 
         // 3. Store locally
         self.state.channels.create(peer, channel.clone())?;
+        self.record_channel_deposit(&channel_id, deposit);
 
         // 4. Send ChannelOpen message (if network available)
         if let Some(network) = self.network().cloned() {
@@ -127,6 +194,106 @@ where
         Ok(channel)
     }
 
+    /// Check a channel open from the query path against the auto-open policy.
+    ///
+    /// `peer` is `None` when only the provider's libp2p ID is known; such
+    /// providers are treated as unseen (reputation 0). The approver callback,
+    /// if any, is asked last.
+    pub fn check_auto_open(&self, peer: Option<&PeerId>, deposit: Amount) -> OpsResult<()> {
+        let policy = &self.config.auto_open;
+
+        if !policy.enabled {
+            return Err(OpsError::AutoOpenDenied("auto-open is disabled".into()));
+        }
+
+        if deposit > policy.max_deposit_per_peer {
+            return Err(OpsError::AutoOpenDenied(format!(
+                "deposit {} exceeds per-peer maximum {}",
+                deposit, policy.max_deposit_per_peer
+            )));
+        }
+
+        let active = self.state.channels.count_active()?;
+        if active >= policy.max_total_channels {
+            return Err(OpsError::AutoOpenDenied(format!(
+                "{} channels active, maximum is {}",
+                active, policy.max_total_channels
+            )));
+        }
+
+        let opened = self.auto_opens_today();
+        if opened >= policy.max_opens_per_day {
+            return Err(OpsError::AutoOpenDenied(format!(
+                "{} channels auto-opened today, maximum is {}",
+                opened, policy.max_opens_per_day
+            )));
+        }
+
+        let reputation = match peer {
+            Some(peer) => self
+                .state
+                .peers
+                .get(peer)?
+                .map(|info| info.reputation)
+                .unwrap_or(0),
+            None => 0,
+        };
+        if reputation < policy.min_provider_reputation {
+            return Err(OpsError::AutoOpenDenied(format!(
+                "provider reputation {} below minimum {}",
+                reputation, policy.min_provider_reputation
+            )));
+        }
+
+        if let Some(approver) = &policy.approver {
+            let request = AutoOpenRequest {
+                peer: peer.copied(),
+                deposit,
+                reputation,
+            };
+            if !approver(&request) {
+                return Err(OpsError::AutoOpenDenied("not approved".into()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Open a payment channel on behalf of the query path.
+    ///
+    /// Same as `open_payment_channel`, but subject to the auto-open policy
+    /// and counted against its daily limit.
+    pub async fn auto_open_payment_channel(
+        &self,
+        peer: &PeerId,
+        deposit: Amount,
+    ) -> OpsResult<Channel> {
+        self.check_auto_open(Some(peer), deposit)?;
+        let channel = self.open_payment_channel(peer, deposit).await?;
+        self.mark_auto_open();
+        Ok(channel)
+    }
+
+    /// Open a payment channel to a libp2p peer on behalf of the query path.
+    ///
+    /// Same as `open_payment_channel_to_libp2p`, but subject to the auto-open
+    /// policy and counted against its daily limit.
+    pub async fn auto_open_payment_channel_to_libp2p(
+        &self,
+        libp2p_peer: nodalync_net::PeerId,
+        deposit: Amount,
+    ) -> OpsResult<(Channel, PeerId)> {
+        let known_peer = self
+            .network()
+            .and_then(|network| network.nodalync_peer_id(&libp2p_peer));
+        self.check_auto_open(known_peer.as_ref(), deposit)?;
+        let opened = self
+            .open_payment_channel_to_libp2p(libp2p_peer, deposit)
+            .await?;
+        self.mark_auto_open();
+        Ok(opened)
+    }
+
     /// Open a new payment channel with a peer using their libp2p peer ID directly.
     ///
     /// This is useful when you have the libp2p peer ID (from an announcement)
@@ -135,7 +302,7 @@ where
     ///
     /// Returns the created channel and the remote's Nodalync peer ID.
     pub async fn open_payment_channel_to_libp2p(
-        &mut self,
+        &self,
         libp2p_peer: nodalync_net::PeerId,
         deposit: Amount,
     ) -> OpsResult<(Channel, PeerId)> {
@@ -246,6 +413,7 @@ where
         self.state
             .channels
             .create(&remote_nodalync_id, channel.clone())?;
+        self.record_channel_deposit(&channel.channel_id, deposit);
 
         tracing::info!(
             channel_id = %channel_id,
@@ -265,7 +433,7 @@ where
     /// 2. Creates reciprocal Channel state
     /// 3. Stores
     pub fn accept_payment_channel(
-        &mut self,
+        &self,
         channel_id: &Hash,
         peer: &PeerId,
         their_deposit: Amount,
@@ -283,13 +451,43 @@ where
 
         // 3. Store
         self.state.channels.create(peer, channel.clone())?;
+        self.record_channel_deposit(channel_id, my_deposit);
 
         Ok(channel)
     }
 
     /// Update channel state with a payment.
-    pub fn update_payment_channel(&mut self, peer: &PeerId, payment: Payment) -> OpsResult<()> {
+    pub fn update_payment_channel(&self, peer: &PeerId, payment: Payment) -> OpsResult<()> {
+        self.apply_channel_payment(peer, payment.clone())?;
+
+        // Record in the ledger
+        if payment.recipient == self.peer_id() {
+            self.record_query_revenue(
+                payment.query_hash,
+                payment.amount,
+                &payment.recipient,
+                &payment.provenance,
+                None,
+            );
+        } else {
+            self.record_ledger_transfer(
+                LedgerEvent::QueryPaid,
+                payment.query_hash,
+                LedgerAccount::Channels,
+                LedgerAccount::QuerySpend,
+                payment.amount,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Apply a payment to the channel with `peer` and add it to pending,
+    /// without recording it in the ledger.
+    pub(crate) fn apply_channel_payment(&self, peer: &PeerId, payment: Payment) -> OpsResult<()> {
         let timestamp = current_timestamp();
+        let channel_lock = self.channel_locks.for_peer(peer);
+        let _guard = lock(&channel_lock);
 
         // Get channel
         let mut channel = self
@@ -314,10 +512,16 @@ where
             channel
                 .pay(payment.clone(), timestamp)
                 .map_err(|_| OpsError::InsufficientChannelBalance)?;
+
+            // Keep the channel at the nonce we actually signed, which may be
+            // ahead if earlier payments were signed but never delivered
+            if let Some(nonces) = self.state.channels.payment_nonces(&channel.channel_id)? {
+                channel.nonce = channel.nonce.max(nonces.last_sent);
+            }
         }
 
-        // Store updated channel
-        self.state.channels.update(peer, &channel)?;
+        // Checkpoint and store updated channel
+        self.commit_channel_state(peer, &channel)?;
 
         // Add payment to pending
         self.state.channels.add_payment(peer, payment)?;
@@ -339,16 +543,36 @@ where
     /// and the user should use `dispute_payment_channel()` instead.
     ///
     /// Requires the private key for signing the close message.
+    #[tracing::instrument(name = "close_channel", skip_all, fields(%peer))]
     pub async fn close_payment_channel(
-        &mut self,
+        &self,
         peer: &PeerId,
         private_key: &PrivateKey,
     ) -> OpsResult<crate::error::CloseResult> {
-        use crate::error::CloseResult;
+        // 1-2. Validate, sign, and record the pending close
+        let (channel, payload) = self.begin_channel_close(peer, private_key)?;
 
+        // 3-4. Send ChannelClose and wait for the peer's signature
+        let responder_signature = request_close_ack(self.network().cloned(), peer, payload).await;
+
+        // 5-6. Submit to chain and mark closed
+        self.finish_channel_close(peer, channel, responder_signature)
+            .await
+    }
+
+    /// Validate a channel for closing, sign the close message, and record
+    /// the pending close.
+    ///
+    /// Returns the channel (with `pending_close` set) and the `ChannelClose`
+    /// payload to send to the peer.
+    pub(crate) fn begin_channel_close(
+        &self,
+        peer: &PeerId,
+        private_key: &PrivateKey,
+    ) -> OpsResult<(Channel, ChannelClosePayload)> {
         let timestamp = current_timestamp();
 
-        // 1. Get channel and validate state
+        // Get channel and validate state
         let mut channel = self
             .state
             .channels
@@ -370,7 +594,7 @@ where
             return Err(OpsError::invalid_operation("channel has a pending dispute"));
         }
 
-        // 2. Compute final balances and sign close message
+        // Compute final balances and sign close message
         let my_balance = channel.my_balance;
         let their_balance = channel.their_balance;
         let nonce = channel.nonce;
@@ -395,57 +619,41 @@ where
         channel.pending_close = Some(pending_close);
         self.state.channels.update(peer, &channel)?;
 
-        // 3. Send ChannelClose with our signature to peer
-        let responder_signature = if let Some(network) = self.network().cloned() {
-            if let Some(libp2p_peer) = network.libp2p_peer_id(peer) {
-                let payload = ChannelClosePayload {
-                    channel_id: channel.channel_id,
-                    nonce,
-                    final_balances,
-                    initiator_signature,
-                };
-
-                // Send and wait for response
-                match network.send_channel_close(libp2p_peer, payload).await {
-                    Ok(response) => {
-                        // Decode the ChannelCloseAck response
-                        match nodalync_wire::decode_payload::<ChannelCloseAckPayload>(
-                            &response.payload,
-                        ) {
-                            Ok(ack) => Some(ack.responder_signature),
-                            Err(e) => {
-                                tracing::warn!(
-                                    error = %e,
-                                    "Failed to decode ChannelCloseAck response"
-                                );
-                                None
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        tracing::warn!(
-                            peer = %peer,
-                            error = %e,
-                            "Peer unresponsive for cooperative close"
-                        );
-                        None
-                    }
-                }
-            } else {
-                tracing::warn!(
-                    peer = %peer,
-                    "No libp2p peer ID mapping for cooperative close"
-                );
-                None
-            }
-        } else {
-            // No network - proceed with off-chain close only
-            None
+        let payload = ChannelClosePayload {
+            channel_id: channel.channel_id,
+            nonce,
+            final_balances,
+            initiator_signature,
         };
 
-        // 4. If peer responded, update pending_close with their signature
+        Ok((channel, payload))
+    }
+
+    /// Finish a close started with `begin_channel_close`.
+    ///
+    /// Without a responder signature the channel keeps its pending close and
+    /// `CloseResult::PeerUnresponsive` is returned. Otherwise the close is
+    /// submitted on-chain (if settlement is configured) and, on success, the
+    /// channel is marked closed.
+    pub(crate) async fn finish_channel_close(
+        &self,
+        peer: &PeerId,
+        mut channel: Channel,
+        responder_signature: Option<Signature>,
+    ) -> OpsResult<crate::error::CloseResult> {
+        use crate::error::CloseResult;
+
+        let timestamp = current_timestamp();
+        let my_balance = channel.my_balance;
+        let their_balance = channel.their_balance;
+        let final_balances = ChannelBalances::new(my_balance, their_balance);
+
+        // If peer responded, update pending_close with their signature
         if let Some(sig) = responder_signature {
-            let mut pending = channel.pending_close.take().unwrap();
+            let mut pending = channel
+                .pending_close
+                .take()
+                .ok_or_else(|| OpsError::invalid_operation("channel has no pending close"))?;
             pending.add_responder_signature(sig);
             channel.pending_close = Some(pending);
             self.state.channels.update(peer, &channel)?;
@@ -458,7 +666,7 @@ where
             });
         }
 
-        // 5. Submit to chain with both signatures (if settlement available)
+        // Submit to chain with both signatures (if settlement available)
         let pending = channel.pending_close.as_ref().unwrap();
         let both_signatures = vec![
             pending.initiator_signature,
@@ -503,7 +711,7 @@ where
             }
         };
 
-        // 6. Update state to Closed (only if successful)
+        // Update state to Closed (only if successful)
         if result.is_success() {
             channel.mark_closing(timestamp);
             channel.pending_close = None;
@@ -511,6 +719,7 @@ where
 
             channel.mark_closed(timestamp);
             self.state.channels.update(peer, &channel)?;
+            self.record_channel_close(&channel.channel_id, my_balance);
         }
 
         Ok(result)
@@ -524,7 +733,7 @@ where
     ///
     /// After 24 hours, call `resolve_dispute()` to finalize.
     pub async fn dispute_payment_channel(
-        &mut self,
+        &self,
         peer: &PeerId,
         private_key: &PrivateKey,
     ) -> OpsResult<String> {
@@ -555,28 +764,11 @@ where
             .cloned()
             .ok_or_else(|| OpsError::invalid_operation("settlement layer required for disputes"))?;
 
-        // Prepare dispute state
+        // Sign our latest state for the dispute
         let my_balance = channel.my_balance;
         let their_balance = channel.their_balance;
         let nonce = channel.nonce;
-
-        // Sign the state
-        let signature = sign_channel_close(
-            private_key,
-            &channel.channel_id,
-            nonce,
-            my_balance,
-            their_balance,
-        );
-
-        // Create ChannelUpdatePayload for dispute
-        let state = ChannelUpdatePayload {
-            channel_id: channel.channel_id,
-            nonce,
-            balances: ChannelBalances::new(my_balance, their_balance),
-            payments: vec![],
-            signature,
-        };
+        let state = signed_channel_state(&channel, private_key);
 
         // Submit dispute to chain
         let channel_id = nodalync_settle::ChannelId::new(channel.channel_id);
@@ -604,9 +796,26 @@ where
             my_balance,
             their_balance,
         );
-        channel.pending_dispute = Some(pending_dispute);
+        channel.pending_dispute = Some(pending_dispute.clone());
         channel.mark_disputed(timestamp);
         self.state.channels.update(peer, &channel)?;
+        self.state.disputes.track(&DisputeRecord {
+            channel_id: channel.channel_id,
+            peer: *peer,
+            opened_by_us: true,
+            started_at: timestamp,
+            deadline: pending_dispute.resolution_time,
+            disputed_nonce: nonce,
+            counter_tx_id: None,
+            warned_at: None,
+            closed_at: None,
+        })?;
+
+        self.emit(OpsEvent::ChannelDisputed {
+            peer: *peer,
+            channel_id: channel.channel_id,
+            transaction_id: tx_id.to_string(),
+        });
 
         Ok(tx_id.to_string())
     }
@@ -615,7 +824,7 @@ where
     ///
     /// Finalizes the channel close using the latest state submitted during
     /// the dispute period.
-    pub async fn resolve_dispute(&mut self, peer: &PeerId) -> OpsResult<String> {
+    pub async fn resolve_dispute(&self, peer: &PeerId) -> OpsResult<String> {
         let timestamp = current_timestamp();
 
         // Get channel
@@ -663,6 +872,11 @@ where
         channel.pending_dispute = None;
         channel.mark_closed(timestamp);
         self.state.channels.update(peer, &channel)?;
+        if let Some(mut dispute) = self.state.disputes.get(&channel.channel_id)? {
+            dispute.closed_at = Some(timestamp);
+            self.state.disputes.track(&dispute)?;
+        }
+        self.record_channel_close(&channel.channel_id, channel.my_balance);
 
         Ok(tx_id.to_string())
     }
@@ -717,14 +931,342 @@ where
 
     /// Get the next payment nonce for a channel.
     ///
-    /// Returns `channel.nonce + 1` if channel exists and is open.
+    /// Returns one above the channel nonce or the highest tracked payment
+    /// nonce, whichever is greater, if the channel exists and is open.
     pub fn get_next_payment_nonce(&self, peer: &PeerId) -> OpsResult<u64> {
         match self.state.channels.get(peer)? {
-            Some(channel) if channel.is_open() => Ok(channel.nonce + 1),
+            Some(channel) if channel.is_open() => Ok(self.payment_nonce_floor(&channel)? + 1),
             Some(_) => Err(OpsError::ChannelNotOpen),
             None => Err(OpsError::ChannelNotFound),
         }
     }
+
+    /// Highest nonce already used on a channel in either direction.
+    ///
+    /// New payments must be signed above this, even when the channel row is
+    /// behind the tracked nonces after a crash or an undelivered payment.
+    pub(crate) fn payment_nonce_floor(&self, channel: &Channel) -> OpsResult<u64> {
+        let tracked = self
+            .state
+            .channels
+            .payment_nonces(&channel.channel_id)?
+            .map(|nonces| nonces.highest())
+            .unwrap_or(0);
+        Ok(channel.nonce.max(tracked))
+    }
+
+    /// Sign a payment on a channel, recording its nonce first.
+    ///
+    /// The nonce is recorded before the payment leaves this node, so even if
+    /// we crash before the channel is updated the nonce is never reused.
+    pub(crate) fn sign_tracked_payment(
+        &self,
+        peer: &PeerId,
+        channel: &Channel,
+        amount: Amount,
+        query_hash: Hash,
+        provenance: Vec<ProvenanceEntry>,
+    ) -> OpsResult<(Payment, u64)> {
+        let private_key = self.private_key().ok_or(OpsError::PrivateKeyRequired)?;
+
+        let mut signing_channel = channel.clone();
+        signing_channel.nonce = self.payment_nonce_floor(channel)?;
+        let (payment, nonce) = create_signed_payment(
+            private_key,
+            &signing_channel,
+            amount,
+            *peer,
+            query_hash,
+            provenance,
+        );
+
+        self.state.channels.record_payment_nonce(
+            &channel.channel_id,
+            peer,
+            PaymentDirection::Sent,
+            nonce,
+            current_timestamp(),
+        )?;
+        Ok((payment, nonce))
+    }
+
+    /// Sign the channel's current state with our key, if we have one.
+    fn sign_channel_state(&self, channel: &Channel) -> Signature {
+        match self.private_key() {
+            Some(pk) => sign_channel_close(
+                pk,
+                &channel.channel_id,
+                channel.nonce,
+                channel.my_balance,
+                channel.their_balance,
+            ),
+            None => Signature::from_bytes([0u8; 64]),
+        }
+    }
+
+    /// Persist a new channel state, checkpointing it first.
+    ///
+    /// The signed state is written ahead of the channel row so a crash
+    /// between the two leaves a checkpoint that `recover_payment_channels`
+    /// can roll forward.
+    pub(crate) fn commit_channel_state(&self, peer: &PeerId, channel: &Channel) -> OpsResult<()> {
+        let checkpoint = ChannelCheckpoint::new(
+            channel.channel_id,
+            *peer,
+            channel.nonce,
+            channel.my_balance,
+            channel.their_balance,
+            self.sign_channel_state(channel),
+            channel.last_update,
+        );
+        self.state.channels.checkpoint(&checkpoint)?;
+        self.state.channels.update(peer, channel)?;
+        Ok(())
+    }
+
+    /// Recover open channels after a restart.
+    ///
+    /// For each open channel, rolls the stored state forward to the latest
+    /// checkpoint if the node crashed mid-update, and reports nonce gaps
+    /// where the counterparty advanced the channel through states we never
+    /// recorded. Only channels that needed attention are returned; those
+    /// with gaps should be passed to `resync_payment_channel`.
+    pub fn recover_payment_channels(&self) -> OpsResult<Vec<ChannelRecovery>> {
+        let timestamp = current_timestamp();
+        let mut recovered = Vec::new();
+
+        for (peer, mut channel) in self.state.channels.list_open()? {
+            let Some(latest) = self.state.channels.latest_checkpoint(&channel.channel_id)? else {
+                continue;
+            };
+
+            let stored_nonce = channel.nonce;
+            let restored = latest.nonce > channel.nonce;
+            if restored {
+                channel.nonce = latest.nonce;
+                channel.my_balance = latest.my_balance;
+                channel.their_balance = latest.their_balance;
+                channel.last_update = timestamp;
+                self.state.channels.update(&peer, &channel)?;
+
+                tracing::warn!(
+                    channel_id = %channel.channel_id,
+                    stored_nonce = stored_nonce,
+                    checkpoint_nonce = latest.nonce,
+                    "Rolled channel forward to latest checkpoint"
+                );
+            }
+
+            let gaps = self.state.channels.checkpoint_gaps(&channel.channel_id)?;
+            if !gaps.is_empty() {
+                tracing::warn!(
+                    channel_id = %channel.channel_id,
+                    gaps = gaps.len(),
+                    "Channel has nonce gaps, resync required"
+                );
+            }
+
+            if restored || !gaps.is_empty() {
+                recovered.push(ChannelRecovery {
+                    peer,
+                    channel_id: channel.channel_id,
+                    stored_nonce,
+                    checkpoint_nonce: latest.nonce,
+                    restored,
+                    gaps,
+                });
+            }
+        }
+
+        Ok(recovered)
+    }
+
+    /// Reconcile channel nonces with the tracked payment nonces.
+    ///
+    /// Moves each open channel's nonce up to the highest nonce known for it:
+    /// the latest checkpoint or a payment nonce tracked in either direction.
+    /// A crash between recording a nonce and updating the channel otherwise
+    /// leaves the channel behind, and the next payment or close would reuse
+    /// a nonce the counterparty has already seen. Run after
+    /// `recover_payment_channels`; only channels that changed are returned.
+    pub fn reconcile_payment_nonces(&self) -> OpsResult<Vec<NonceReconciliation>> {
+        let timestamp = current_timestamp();
+        let mut reconciled = Vec::new();
+
+        for (peer, mut channel) in self.state.channels.list_open()? {
+            let Some(nonces) = self.state.channels.payment_nonces(&channel.channel_id)? else {
+                continue;
+            };
+            let checkpoint_nonce = self
+                .state
+                .channels
+                .latest_checkpoint(&channel.channel_id)?
+                .map(|checkpoint| checkpoint.nonce)
+                .unwrap_or(0);
+
+            let recovered_nonce = nonces.highest().max(checkpoint_nonce);
+            if recovered_nonce <= channel.nonce {
+                continue;
+            }
+
+            let stored_nonce = channel.nonce;
+            channel.nonce = recovered_nonce;
+            channel.last_update = timestamp;
+            self.commit_channel_state(&peer, &channel)?;
+
+            tracing::warn!(
+                channel_id = %channel.channel_id,
+                stored_nonce = stored_nonce,
+                recovered_nonce = recovered_nonce,
+                "Reconciled channel nonce with tracked payment nonces"
+            );
+
+            reconciled.push(NonceReconciliation {
+                peer,
+                channel_id: channel.channel_id,
+                stored_nonce,
+                last_sent: nonces.last_sent,
+                last_received: nonces.last_received,
+                recovered_nonce,
+            });
+        }
+
+        Ok(reconciled)
+    }
+
+    /// Build a sync payload carrying our latest signed state.
+    pub(crate) fn channel_sync_payload(&self, channel: &Channel) -> ChannelSyncPayload {
+        ChannelSyncPayload {
+            channel_id: channel.channel_id,
+            nonce: channel.nonce,
+            balances: ChannelBalances::new(channel.my_balance, channel.their_balance),
+            signature: self.sign_channel_state(channel),
+        }
+    }
+
+    /// Resync a channel with its counterparty.
+    ///
+    /// Sends our latest signed state and adopts the peer's state if it is
+    /// ahead of ours. Returns `true` if the local state changed.
+    pub async fn resync_payment_channel(&self, peer: &PeerId) -> OpsResult<bool> {
+        let channel = self
+            .state
+            .channels
+            .get(peer)?
+            .ok_or(OpsError::ChannelNotFound)?;
+
+        let network = self
+            .network()
+            .cloned()
+            .ok_or_else(|| OpsError::invalid_operation("network required for channel resync"))?;
+        let libp2p_peer = network.libp2p_peer_id(peer).ok_or_else(|| {
+            OpsError::invalid_operation("no libp2p peer ID mapping for channel resync")
+        })?;
+
+        let request = self.channel_sync_payload(&channel);
+        let response = network
+            .send_channel_sync(libp2p_peer, request)
+            .await
+            .map_err(|e| OpsError::invalid_operation(format!("channel resync failed: {}", e)))?;
+
+        self.apply_channel_sync(
+            peer,
+            &response.channel_id,
+            response.nonce,
+            &response.balances,
+            &response.signature,
+        )
+    }
+
+    /// Apply a counterparty's signed channel state.
+    ///
+    /// `balances` are from the counterparty's perspective (`initiator` is
+    /// theirs). The state is adopted if its nonce is higher than ours, after
+    /// checking the signature (when their key is known) and that the total
+    /// channel capacity is unchanged. Checkpoints below the agreed nonce are
+    /// pruned so resolved gaps are not reported again.
+    pub(crate) fn apply_channel_sync(
+        &self,
+        peer: &PeerId,
+        channel_id: &Hash,
+        nonce: u64,
+        balances: &ChannelBalances,
+        signature: &Signature,
+    ) -> OpsResult<bool> {
+        let mut channel = self
+            .state
+            .channels
+            .get(peer)?
+            .ok_or(OpsError::ChannelNotFound)?;
+
+        if channel.channel_id != *channel_id {
+            return Err(OpsError::invalid_operation("channel ID mismatch"));
+        }
+
+        if channel.is_closed() {
+            return Err(OpsError::invalid_operation("channel already closed"));
+        }
+
+        let adopt = nonce > channel.nonce;
+        if adopt {
+            if balances.initiator.checked_add(balances.responder)
+                != channel.my_balance.checked_add(channel.their_balance)
+            {
+                return Err(OpsError::invalid_operation(
+                    "synced balances do not match channel capacity",
+                ));
+            }
+
+            // Soft-fail: if peer key is unknown, skip verification (consistent with close)
+            let peer_pubkey = self
+                .state
+                .peers
+                .get(peer)
+                .ok()
+                .flatten()
+                .map(|info| info.public_key)
+                .filter(|pk| pk.0 != [0u8; 32]);
+            if let Some(pubkey) = peer_pubkey {
+                if !verify_channel_close_signature(
+                    &pubkey,
+                    channel_id,
+                    nonce,
+                    balances.initiator,
+                    balances.responder,
+                    signature,
+                ) {
+                    return Err(OpsError::invalid_operation(
+                        "invalid signature on synced channel state",
+                    ));
+                }
+            }
+
+            channel.nonce = nonce;
+            channel.their_balance = balances.initiator;
+            channel.my_balance = balances.responder;
+            channel.last_update = current_timestamp();
+            self.commit_channel_state(peer, &channel)?;
+
+            tracing::info!(
+                channel_id = %channel.channel_id,
+                nonce = nonce,
+                "Adopted counterparty channel state"
+            );
+        } else if nonce == channel.nonce
+            && (balances.initiator != channel.their_balance
+                || balances.responder != channel.my_balance)
+        {
+            return Err(OpsError::invalid_operation(
+                "conflicting channel state at the same nonce",
+            ));
+        }
+
+        self.state
+            .channels
+            .prune_checkpoints(&channel.channel_id, channel.nonce)?;
+
+        Ok(adopt)
+    }
 }
 
 /// Sign a payment with the given private key.
@@ -735,6 +1277,53 @@ pub fn sign_payment(private_key: &PrivateKey, payment: &Payment) -> Signature {
     sign(private_key, &message)
 }
 
+/// Send a `ChannelClose` to the peer and wait for its `ChannelCloseAck`.
+///
+/// Returns the responder's signature, or `None` if there is no network, no
+/// libp2p mapping for the peer, or the peer did not answer. Takes the
+/// network by value so several closes can run concurrently.
+pub(crate) async fn request_close_ack(
+    network: Option<Arc<dyn Network>>,
+    peer: &PeerId,
+    payload: ChannelClosePayload,
+) -> Option<Signature> {
+    // No network - proceed with off-chain close only
+    let network = network?;
+
+    let Some(libp2p_peer) = network.libp2p_peer_id(peer) else {
+        tracing::warn!(
+            peer = %peer,
+            "No libp2p peer ID mapping for cooperative close"
+        );
+        return None;
+    };
+
+    // Send and wait for response
+    match network.send_channel_close(libp2p_peer, payload).await {
+        Ok(response) => {
+            // Decode the ChannelCloseAck response
+            match nodalync_wire::decode_payload::<ChannelCloseAckPayload>(&response.payload) {
+                Ok(ack) => Some(ack.responder_signature),
+                Err(e) => {
+                    tracing::warn!(
+                        error = %e,
+                        "Failed to decode ChannelCloseAck response"
+                    );
+                    None
+                }
+            }
+        }
+        Err(e) => {
+            tracing::warn!(
+                peer = %peer,
+                error = %e,
+                "Peer unresponsive for cooperative close"
+            );
+            None
+        }
+    }
+}
+
 /// Create a signed payment for a query.
 ///
 /// This function creates a payment with proper signature for submitting
@@ -801,6 +1390,7 @@ pub fn create_signed_payment_for_manifest(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::AutoOpenPolicy;
     use crate::node_ops::DefaultNodeOperations;
     use nodalync_crypto::{generate_identity, peer_id_from_public_key, Signature};
     use nodalync_store::NodeStateConfig;
@@ -840,7 +1430,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_open_channel() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let peer = test_peer_id();
 
         let channel = ops
@@ -856,7 +1446,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_open_channel_already_exists() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let peer = test_peer_id();
 
         ops.open_payment_channel(&peer, 100_0000_0000)
@@ -870,7 +1460,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_open_channel_min_deposit() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let peer = test_peer_id();
 
         // Below minimum deposit
@@ -880,7 +1470,7 @@ mod tests {
 
     #[test]
     fn test_accept_channel() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let peer = test_peer_id();
         let channel_id = content_hash(b"channel");
 
@@ -896,7 +1486,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_close_channel() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let (private_key, _public_key) = generate_identity();
         let peer = test_peer_id();
         let channel_id = content_hash(b"channel");
@@ -924,7 +1514,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_close_channel_cooperative() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let (private_key, _public_key) = generate_identity();
         let peer = test_peer_id();
         let channel_id = content_hash(b"channel");
@@ -959,7 +1549,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_has_open_channel() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let peer = test_peer_id();
 
         // No channel
@@ -981,7 +1571,7 @@ mod tests {
 
     #[test]
     fn test_get_channel_balance() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let peer = test_peer_id();
         let channel_id = content_hash(b"channel");
 
@@ -1050,7 +1640,7 @@ mod tests {
 
     #[test]
     fn test_get_next_payment_nonce() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let peer = test_peer_id();
         let channel_id = content_hash(b"channel");
 
@@ -1066,11 +1656,54 @@ mod tests {
 
         // Should get nonce = 1 (channel nonce starts at 0)
         assert_eq!(ops.get_next_payment_nonce(&peer).unwrap(), 1);
+
+        // A signed but undelivered payment still consumes its nonce
+        ops.state
+            .channels
+            .record_payment_nonce(&channel_id, &peer, PaymentDirection::Sent, 1, 100)
+            .unwrap();
+        assert_eq!(ops.get_next_payment_nonce(&peer).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_reconcile_payment_nonces_after_crash() {
+        let (ops, _temp) = create_test_ops();
+        let peer = test_peer_id();
+        let channel_id = content_hash(b"nonce-crash-channel");
+
+        ops.accept_payment_channel(&channel_id, &peer, 500, 1000)
+            .unwrap();
+
+        // Nothing tracked yet, nothing to reconcile
+        assert!(ops.reconcile_payment_nonces().unwrap().is_empty());
+
+        // Payments were accepted and sent, but the channel row was never updated
+        for (direction, nonce) in [(PaymentDirection::Received, 4), (PaymentDirection::Sent, 6)] {
+            ops.state
+                .channels
+                .record_payment_nonce(&channel_id, &peer, direction, nonce, 100)
+                .unwrap();
+        }
+
+        let reconciled = ops.reconcile_payment_nonces().unwrap();
+        assert_eq!(reconciled.len(), 1);
+        assert_eq!(reconciled[0].stored_nonce, 0);
+        assert_eq!(reconciled[0].last_received, 4);
+        assert_eq!(reconciled[0].last_sent, 6);
+        assert_eq!(reconciled[0].recovered_nonce, 6);
+
+        let channel = ops.get_payment_channel(&peer).unwrap().unwrap();
+        assert_eq!(channel.nonce, 6);
+        assert_eq!(channel.my_balance, 1000);
+        assert_eq!(ops.get_next_payment_nonce(&peer).unwrap(), 7);
+
+        // A second pass has nothing left to do
+        assert!(ops.reconcile_payment_nonces().unwrap().is_empty());
     }
 
     #[test]
     fn test_update_payment_channel_success() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let peer = test_peer_id();
         let channel_id = content_hash(b"update-channel");
 
@@ -1098,9 +1731,49 @@ mod tests {
         assert_eq!(channel.my_balance, 900);
     }
 
+    #[test]
+    fn test_concurrent_payments_on_one_channel() {
+        let (ops, _temp) = create_test_ops();
+        let peer = test_peer_id();
+        let channel_id = content_hash(b"concurrent-channel");
+        ops.accept_payment_channel(&channel_id, &peer, 500, 1000)
+            .unwrap();
+
+        std::thread::scope(|scope| {
+            for i in 0..10u8 {
+                let ops = &ops;
+                scope.spawn(move || {
+                    let payment = Payment::new(
+                        content_hash(&[i]),
+                        channel_id,
+                        10,
+                        peer,
+                        content_hash(b"query"),
+                        vec![],
+                        current_timestamp(),
+                        Signature::from_bytes([0u8; 64]),
+                    );
+                    ops.update_payment_channel(&peer, payment).unwrap();
+                });
+            }
+        });
+
+        // No payment's update was lost to another's
+        let channel = ops.get_payment_channel(&peer).unwrap().unwrap();
+        assert_eq!(channel.my_balance, 900);
+        assert_eq!(
+            ops.state
+                .channels
+                .get_pending_payments(&peer)
+                .unwrap()
+                .len(),
+            10
+        );
+    }
+
     #[test]
     fn test_update_payment_channel_insufficient_balance() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let peer = test_peer_id();
         let channel_id = content_hash(b"insuff-channel");
 
@@ -1130,7 +1803,7 @@ mod tests {
 
     #[test]
     fn test_get_payment_channel_existing() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let peer = test_peer_id();
         let channel_id = content_hash(b"get-channel");
 
@@ -1220,4 +1893,198 @@ mod tests {
             "Signing same data should produce same signature"
         );
     }
+
+    fn checkpoint_at(channel_id: Hash, peer: PeerId, nonce: u64, my: Amount) -> ChannelCheckpoint {
+        ChannelCheckpoint::new(
+            channel_id,
+            peer,
+            nonce,
+            my,
+            1500 - my,
+            Signature::from_bytes([0u8; 64]),
+            current_timestamp(),
+        )
+    }
+
+    #[test]
+    fn test_update_payment_channel_writes_checkpoint() {
+        let (ops, _temp) = create_test_ops();
+        let peer = test_peer_id();
+        let channel_id = content_hash(b"checkpoint-channel");
+
+        ops.accept_payment_channel(&channel_id, &peer, 500, 1000)
+            .unwrap();
+        ops.update_payment_channel(&peer, test_payment(channel_id, 100, peer))
+            .unwrap();
+
+        let checkpoint = ops
+            .state
+            .channels
+            .latest_checkpoint(&channel_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(checkpoint.nonce, 1);
+        assert_eq!(checkpoint.my_balance, 900);
+        assert_eq!(checkpoint.their_balance, 600);
+    }
+
+    #[test]
+    fn test_recover_rolls_forward_to_checkpoint() {
+        let (ops, _temp) = create_test_ops();
+        let peer = test_peer_id();
+        let channel_id = content_hash(b"crash-channel");
+
+        ops.accept_payment_channel(&channel_id, &peer, 500, 1000)
+            .unwrap();
+
+        // Simulate a crash after the checkpoint but before the channel row update
+        for nonce in 1..=2 {
+            let checkpoint = checkpoint_at(channel_id, peer, nonce, 1000 - nonce * 100);
+            ops.state.channels.checkpoint(&checkpoint).unwrap();
+        }
+
+        let recovered = ops.recover_payment_channels().unwrap();
+        assert_eq!(recovered.len(), 1);
+        assert!(recovered[0].restored);
+        assert!(!recovered[0].needs_resync());
+        assert_eq!(recovered[0].stored_nonce, 0);
+        assert_eq!(recovered[0].checkpoint_nonce, 2);
+
+        let channel = ops.get_payment_channel(&peer).unwrap().unwrap();
+        assert_eq!(channel.nonce, 2);
+        assert_eq!(channel.my_balance, 800);
+        assert_eq!(channel.their_balance, 700);
+
+        // A second pass has nothing left to do
+        assert!(ops.recover_payment_channels().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_recover_reports_gaps_and_sync_resolves_them() {
+        let (ops, _temp) = create_test_ops();
+        let peer = test_peer_id();
+        let channel_id = content_hash(b"gap-channel");
+
+        ops.accept_payment_channel(&channel_id, &peer, 500, 1000)
+            .unwrap();
+        for (nonce, my) in [(1, 900), (4, 800)] {
+            let checkpoint = checkpoint_at(channel_id, peer, nonce, my);
+            ops.state.channels.checkpoint(&checkpoint).unwrap();
+        }
+
+        let recovered = ops.recover_payment_channels().unwrap();
+        assert_eq!(recovered.len(), 1);
+        assert_eq!(recovered[0].gaps, vec![(2, 3)]);
+        assert!(recovered[0].needs_resync());
+
+        // Peer is ahead at nonce 6 (balances from their perspective)
+        let adopted = ops
+            .apply_channel_sync(
+                &peer,
+                &channel_id,
+                6,
+                &ChannelBalances::new(750, 750),
+                &Signature::from_bytes([0u8; 64]),
+            )
+            .unwrap();
+        assert!(adopted);
+
+        let channel = ops.get_payment_channel(&peer).unwrap().unwrap();
+        assert_eq!(channel.nonce, 6);
+        assert_eq!(channel.my_balance, 750);
+        assert!(ops.recover_payment_channels().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_apply_channel_sync_rejects_bad_state() {
+        let (ops, _temp) = create_test_ops();
+        let peer = test_peer_id();
+        let channel_id = content_hash(b"bad-sync-channel");
+
+        ops.accept_payment_channel(&channel_id, &peer, 500, 1000)
+            .unwrap();
+        let sig = Signature::from_bytes([0u8; 64]);
+
+        // Capacity changed
+        let result =
+            ops.apply_channel_sync(&peer, &channel_id, 3, &ChannelBalances::new(500, 500), &sig);
+        assert!(matches!(result, Err(OpsError::InvalidOperation(_))));
+
+        // Same nonce, different balances
+        let result =
+            ops.apply_channel_sync(&peer, &channel_id, 0, &ChannelBalances::new(600, 900), &sig);
+        assert!(matches!(result, Err(OpsError::InvalidOperation(_))));
+
+        // Older state is ignored
+        let channel = ops.get_payment_channel(&peer).unwrap().unwrap();
+        assert_eq!(channel.nonce, 0);
+        assert_eq!(channel.my_balance, 1000);
+    }
+
+    #[tokio::test]
+    async fn test_auto_open_policy_limits() {
+        let (mut ops, _temp) = create_test_ops();
+        ops.config.auto_open = AutoOpenPolicy::default()
+            .with_max_deposit_per_peer(200_0000_0000)
+            .with_max_opens_per_day(2);
+
+        // Over the per-peer deposit cap
+        let result = ops
+            .auto_open_payment_channel(&test_peer_id(), 300_0000_0000)
+            .await;
+        assert!(matches!(result, Err(OpsError::AutoOpenDenied(_))));
+
+        for _ in 0..2 {
+            ops.auto_open_payment_channel(&test_peer_id(), 100_0000_0000)
+                .await
+                .unwrap();
+        }
+        assert_eq!(ops.auto_opens_today(), 2);
+
+        // Daily limit reached
+        let result = ops
+            .auto_open_payment_channel(&test_peer_id(), 100_0000_0000)
+            .await;
+        assert!(matches!(result, Err(OpsError::AutoOpenDenied(_))));
+
+        // Explicit opens are not subject to the policy
+        ops.open_payment_channel(&test_peer_id(), 100_0000_0000)
+            .await
+            .unwrap();
+
+        // Total channel limit
+        ops.config.auto_open = AutoOpenPolicy::default().with_max_total_channels(3);
+        let result = ops
+            .auto_open_payment_channel(&test_peer_id(), 100_0000_0000)
+            .await;
+        assert!(matches!(result, Err(OpsError::AutoOpenDenied(_))));
+
+        ops.config.auto_open = AutoOpenPolicy::disabled();
+        assert!(ops.check_auto_open(None, 1).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_auto_open_reputation_and_approver() {
+        let (mut ops, _temp) = create_test_ops();
+        let (_, public_key) = generate_identity();
+        let peer = peer_id_from_public_key(&public_key);
+        ops.state
+            .peers
+            .upsert(&nodalync_store::PeerInfo::new(peer, public_key, vec![], 0).with_reputation(-5))
+            .unwrap();
+
+        let result = ops.auto_open_payment_channel(&peer, 100_0000_0000).await;
+        assert!(matches!(result, Err(OpsError::AutoOpenDenied(_))));
+
+        // Unknown peers count as reputation 0
+        assert!(ops.check_auto_open(Some(&test_peer_id()), 1).is_ok());
+
+        ops.config.auto_open = AutoOpenPolicy::default()
+            .with_min_provider_reputation(-10)
+            .with_approver(move |req| req.peer != Some(peer));
+        assert!(ops.check_auto_open(Some(&test_peer_id()), 1).is_ok());
+        let result = ops.auto_open_payment_channel(&peer, 100_0000_0000).await;
+        assert!(matches!(result, Err(OpsError::AutoOpenDenied(_))));
+        assert_eq!(ops.auto_opens_today(), 0);
+    }
 }
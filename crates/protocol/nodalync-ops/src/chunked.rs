@@ -0,0 +1,618 @@
+//! Chunk-verified downloads from multiple providers.
+//!
+//! Content larger than one chunk records a [`ChunkTree`] in its manifest:
+//! the hash of every [`CHUNK_SIZE`](nodalync_types::CHUNK_SIZE) chunk. A
+//! chunked download spreads chunk requests across every provider that may
+//! hold the content and checks each chunk against the tree as it arrives.
+//! A provider that sends a corrupt chunk, or fails to answer, is dropped
+//! and the chunk is re-fetched from another one, so a single bad provider
+//! never forces the whole download to restart.
+//!
+//! Only free content is served in chunks. A chunk request carries no
+//! payment, so any node holding a copy (stored or cached) may serve it.
+//!
+//! [`ChunkTree`]: nodalync_types::ChunkTree
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use futures::future::join_all;
+use futures::stream::{self, StreamExt};
+use nodalync_crypto::{content_hash, Hash, PeerId, Signature, UNKNOWN_PEER_ID};
+use nodalync_net::{Network, NetworkResult};
+use nodalync_store::{CacheStore, CachedContent, ManifestStore};
+use nodalync_types::{ChunkTree, Manifest, Visibility};
+use nodalync_valid::{validate_embargo, validate_metadata, Validator};
+use nodalync_wire::{
+    Capability, ChunkRequestPayload, ChunkResponsePayload, PaymentReceipt, PreviewRequestPayload,
+};
+use tracing::{debug, info, instrument, warn};
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::helpers::verify_content_hash;
+use crate::node_ops::{current_timestamp, NodeOperations};
+use crate::ops::QueryResponse;
+
+/// How many chunks are hashed at once while downloading.
+const MAX_CHUNK_CHECKS: usize = 8;
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Handle an incoming chunk request.
+    ///
+    /// 1. Load manifest
+    /// 2. Validate access, refusing withdrawn, embargoed, private and
+    ///    restricted content
+    /// 3. Require free content with a chunk tree
+    /// 4. Return the chunk from stored content, or from a cached copy
+    #[instrument(
+        name = "handle_chunk",
+        skip_all,
+        fields(hash = %request.hash, index = request.index, peer = %requester)
+    )]
+    pub async fn handle_chunk_request(
+        &self,
+        requester: &PeerId,
+        request: &ChunkRequestPayload,
+    ) -> OpsResult<ChunkResponsePayload> {
+        // 1. Load manifest
+        let manifest = self
+            .state
+            .manifests
+            .load(&request.hash)?
+            .ok_or(OpsError::ManifestNotFound(request.hash))?;
+
+        // 2. Validate access. Restricted content is only delivered
+        // encrypted, which chunks are not.
+        self.ensure_not_withdrawn(&manifest)?;
+        validate_embargo(&manifest, current_timestamp())?;
+        if matches!(
+            manifest.visibility,
+            Visibility::Private | Visibility::Offline
+        ) || manifest.requires_encryption()
+        {
+            return Err(OpsError::AccessDenied);
+        }
+        let groups = self.resolve_groups(&manifest).await;
+        self.validator
+            .validate_access_with_groups(requester, &manifest, &groups)?;
+
+        // 3. Free, chunked content only
+        nodalync_valid::validate_free_query(&manifest)
+            .map_err(|_| OpsError::PaymentInsufficient)?;
+        let range = manifest
+            .metadata
+            .chunks
+            .as_ref()
+            .ok_or_else(|| OpsError::invalid_operation("content has no chunk tree"))?
+            .chunk_range(request.index, manifest.metadata.content_size)
+            .ok_or_else(|| {
+                OpsError::invalid_operation(format!("chunk index {} out of range", request.index))
+            })?;
+
+        // 4. Load the chunk
+        let data = match self.state.content.load_mapped(&request.hash)? {
+            Some(content) => content.get(range).map(<[u8]>::to_vec),
+            None => self
+                .state
+                .cache
+                .get(&request.hash)?
+                .and_then(|cached| cached.content.get(range).map(<[u8]>::to_vec)),
+        };
+        let data = data.ok_or(OpsError::NotFound(request.hash))?;
+
+        debug!(hash = %request.hash, index = request.index, requester = %requester, "Served chunk");
+
+        Ok(ChunkResponsePayload {
+            hash: request.hash,
+            index: request.index,
+            data,
+        })
+    }
+
+    /// Peers known to hold content: its owner and the publisher from its
+    /// announcement, other than us.
+    pub(crate) fn content_providers(
+        &self,
+        network: &Arc<dyn Network>,
+        hash: &Hash,
+        owner: &PeerId,
+    ) -> Vec<nodalync_net::PeerId> {
+        let mut providers = Vec::new();
+        if *owner != self.peer_id() && *owner != UNKNOWN_PEER_ID {
+            providers.extend(network.libp2p_peer_id(owner));
+        }
+        if let Some(publisher) = self
+            .state
+            .get_announcement(hash)
+            .and_then(|a| a.publisher_peer_id)
+            .and_then(|p| p.parse::<nodalync_net::PeerId>().ok())
+        {
+            if publisher != network.local_peer_id() && !providers.contains(&publisher) {
+                providers.push(publisher);
+            }
+        }
+        providers
+    }
+
+    /// Download free content chunk by chunk from every provider that may
+    /// hold it.
+    ///
+    /// 1. Load the manifest locally, or preview it, and require a chunk tree
+    /// 2. Collect providers: the owner, the announcing publisher, and
+    ///    connected peers, leaving out peers that advertise capabilities
+    ///    without chunked transfers, best ranked first
+    /// 3. Request one pending chunk from each provider per round, checking
+    ///    the round's chunks against the tree in parallel. A provider that
+    ///    sends a corrupt chunk or fails to answer is dropped, and the chunk
+    ///    goes back to the front of the queue for another provider
+    /// 4. Verify the assembled content against the manifest
+    /// 5. Cache the content and store the manifest
+    ///
+    /// Nothing authenticates the chunk tree itself, so once every provider
+    /// left has disagreed with it, the tree is blamed instead: it is
+    /// fetched again from the content's owner or publisher, once, and if it
+    /// differs the download restarts with it and the providers that
+    /// disagreed. The content hash check in step 4 still decides.
+    ///
+    /// Fails with [`OpsError::ChunkUnavailable`] once no provider is left
+    /// for a chunk still pending.
+    #[instrument(name = "download_chunked", skip(self), fields(%hash))]
+    pub async fn download_chunked(&self, hash: &Hash) -> OpsResult<QueryResponse> {
+        let timestamp = current_timestamp();
+        let network = self.network().cloned().ok_or(OpsError::NotFound(*hash))?;
+
+        // 1. Load the manifest and its chunk tree
+        let mut manifest = match self.state.manifests.load(hash)? {
+            Some(manifest) => manifest,
+            None => self.preview_content(hash).await?.manifest,
+        };
+        validate_metadata(&manifest)?;
+        nodalync_valid::validate_free_query(&manifest)
+            .map_err(|_| OpsError::PaymentInsufficient)?;
+        self.ensure_trusted(&manifest)?;
+        let mut tree = manifest
+            .metadata
+            .chunks
+            .clone()
+            .map(Arc::new)
+            .ok_or_else(|| OpsError::invalid_operation("content has no chunk tree"))?;
+
+        // 2. Collect providers
+        let local = network.local_peer_id();
+        let mut providers = self.content_providers(&network, hash, &manifest.owner);
+        for peer in network.connected_peers() {
+            if peer != local && !providers.contains(&peer) {
+                providers.push(peer);
+            }
+        }
+        providers.retain(|p| self.libp2p_peer_supports(p, Capability::ChunkedTransfer));
+        self.rank_providers(&network, &mut providers);
+
+        // 3. Fetch and check chunks, one per provider per round
+        let mut chunks: Vec<Option<Vec<u8>>> = vec![None; tree.len()];
+        let mut pending: VecDeque<u32> = (0..tree.len() as u32).collect();
+        let mut disagreeing = Vec::new();
+        let mut tree_refetched = false;
+        let mut tree_replaced = false;
+        while let Some(&next) = pending.front() {
+            if providers.is_empty() && !disagreeing.is_empty() && !tree_refetched {
+                tree_refetched = true;
+                if let Some(fresh) = self.refetch_chunk_tree(&network, &manifest, &tree).await {
+                    warn!(hash = %hash, providers = disagreeing.len(), "Every provider disagreed with the chunk tree, using the provider's");
+                    manifest.metadata.chunks = Some(fresh.clone());
+                    tree_replaced = true;
+                    tree = Arc::new(fresh);
+                    providers = std::mem::take(&mut disagreeing);
+                    chunks = vec![None; tree.len()];
+                    pending = (0..tree.len() as u32).collect();
+                    continue;
+                }
+            }
+            if providers.is_empty() {
+                return Err(OpsError::ChunkUnavailable {
+                    hash: *hash,
+                    index: next,
+                });
+            }
+
+            let mut round = Vec::new();
+            for &provider in &providers {
+                let Some(index) = pending.pop_front() else {
+                    break;
+                };
+                round.push((provider, index));
+            }
+            let responses = join_all(round.iter().map(|&(provider, index)| {
+                network.request_chunk(provider, ChunkRequestPayload { hash: *hash, index })
+            }))
+            .await;
+
+            // Hash the round's chunks on the blocking pool, a bounded
+            // number at a time
+            let checks: Vec<_> = round
+                .iter()
+                .zip(responses)
+                .map(|(&(_, index), response)| {
+                    let tree = Arc::clone(&tree);
+                    async move {
+                        let chunk = response?;
+                        if chunk.index != index {
+                            return Ok(None);
+                        }
+                        let verified = tokio::task::spawn_blocking(move || {
+                            tree.verify_chunk(index, &chunk.data).then_some(chunk.data)
+                        })
+                        .await;
+                        Ok(verified.unwrap_or(None))
+                    }
+                })
+                .collect();
+            let verified: Vec<NetworkResult<Option<Vec<u8>>>> = stream::iter(checks)
+                .buffered(MAX_CHUNK_CHECKS)
+                .collect()
+                .await;
+
+            for ((provider, index), chunk) in round.into_iter().zip(verified) {
+                match chunk {
+                    Ok(Some(data)) => {
+                        chunks[index as usize] = Some(data);
+                        continue;
+                    }
+                    Ok(None) => {
+                        warn!(hash = %hash, provider = %provider, index, "Provider sent a corrupt chunk");
+                        disagreeing.push(provider);
+                    }
+                    Err(e) => {
+                        debug!(hash = %hash, provider = %provider, index, error = %e, "Chunk request failed");
+                    }
+                }
+                providers.retain(|p| *p != provider);
+                pending.push_front(index);
+            }
+        }
+
+        // 4. Verify the assembled content
+        let content = chunks.into_iter().flatten().collect::<Vec<_>>().concat();
+        if !verify_content_hash(&content, hash) {
+            return Err(OpsError::ContentHashMismatch);
+        }
+        self.validator.validate_content(&content, &manifest)?;
+        self.check_remote_metadata(&mut manifest);
+        self.screen_remote_content(&manifest)?;
+
+        // 5. Cache with a free receipt (chunks are only served for free content)
+        let receipt = PaymentReceipt {
+            payment_id: content_hash(&[hash.0.as_slice(), &timestamp.to_be_bytes()].concat()),
+            amount: 0,
+            timestamp,
+            channel_nonce: 0,
+            distributor_signature: Signature::from_bytes([0u8; 64]),
+            app_fee: 0,
+            app_fee_recipient: None,
+            referral_fee: 0,
+            referrer: None,
+        };
+        let cached = CachedContent::new(
+            *hash,
+            content.clone(),
+            manifest.owner,
+            timestamp,
+            receipt.clone(),
+        );
+        self.state.cache.cache(cached)?;
+        self.store_remote_manifest(&manifest)?;
+        if tree_replaced {
+            // The content matched the tree fetched again, not the one we held
+            self.state.manifests.update(&manifest)?;
+        }
+
+        info!(hash = %hash, chunks = tree.len(), "Downloaded chunked content");
+
+        Ok(QueryResponse {
+            content,
+            manifest,
+            receipt,
+            bundle: Vec::new(),
+        })
+    }
+
+    /// Fetch the chunk tree again from the content's owner or announcing
+    /// publisher, for a tree every provider disagreed with; `None` if none
+    /// of them has a different one.
+    async fn refetch_chunk_tree(
+        &self,
+        network: &Arc<dyn Network>,
+        manifest: &Manifest,
+        current: &ChunkTree,
+    ) -> Option<ChunkTree> {
+        for provider in self.content_providers(network, &manifest.hash, &manifest.owner) {
+            let request = PreviewRequestPayload {
+                hash: manifest.hash,
+            };
+            let remote = match network.send_preview_request(provider, request).await {
+                Ok(preview) => preview.manifest,
+                Err(e) => {
+                    debug!(hash = %manifest.hash, provider = %provider, error = %e, "Failed to fetch the chunk tree again");
+                    continue;
+                }
+            };
+            if remote.hash != manifest.hash || validate_metadata(&remote).is_err() {
+                continue;
+            }
+            if let Some(tree) = remote.metadata.chunks.filter(|tree| tree != current) {
+                return Some(tree);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, PeerInfo, PeerStore};
+    use nodalync_test_utils::MockNetwork;
+    use nodalync_types::{L1Summary, Metadata, CHUNK_SIZE};
+    use nodalync_wire::PreviewResponsePayload;
+
+    fn create_test_ops() -> DefaultNodeOperations {
+        let state = NodeState::open_in_memory().unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops =
+            DefaultNodeOperations::with_defaults(state, peer_id_from_public_key(&public_key));
+        ops.set_private_key(private_key);
+        ops
+    }
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    /// Two and a half chunks of distinct bytes.
+    fn large_content() -> Vec<u8> {
+        (0..CHUNK_SIZE * 5 / 2).map(|i| (i % 251) as u8).collect()
+    }
+
+    async fn publish(ops: &mut DefaultNodeOperations, content: &[u8], price: u64) -> Hash {
+        let hash = ops
+            .create_content(content, Metadata::new("Dataset", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, price)
+            .await
+            .unwrap();
+        hash
+    }
+
+    #[tokio::test]
+    async fn test_handle_chunk_request() {
+        let mut ops = create_test_ops();
+        let content = large_content();
+        let hash = publish(&mut ops, &content, 0).await;
+        let requester = test_peer_id();
+
+        let manifest = ops.state.manifests.load(&hash).unwrap().unwrap();
+        assert_eq!(manifest.metadata.chunks.as_ref().unwrap().len(), 3);
+
+        let chunk = ops
+            .handle_chunk_request(&requester, &ChunkRequestPayload { hash, index: 2 })
+            .await
+            .unwrap();
+        assert_eq!(chunk.data, &content[2 * CHUNK_SIZE as usize..]);
+
+        let result = ops
+            .handle_chunk_request(&requester, &ChunkRequestPayload { hash, index: 3 })
+            .await;
+        assert!(result.is_err());
+
+        // Small content has no chunk tree
+        let small = publish(&mut ops, b"small", 0).await;
+        let result = ops
+            .handle_chunk_request(
+                &requester,
+                &ChunkRequestPayload {
+                    hash: small,
+                    index: 0,
+                },
+            )
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_handle_chunk_request_refuses_paid_and_private() {
+        let mut ops = create_test_ops();
+        let requester = test_peer_id();
+
+        let mut paid_content = large_content();
+        paid_content[0] = 0xFF;
+        let paid = publish(&mut ops, &paid_content, 100).await;
+        let result = ops
+            .handle_chunk_request(
+                &requester,
+                &ChunkRequestPayload {
+                    hash: paid,
+                    index: 0,
+                },
+            )
+            .await;
+        assert!(matches!(result, Err(OpsError::PaymentInsufficient)));
+
+        let private = ops
+            .create_content(
+                &large_content(),
+                Metadata::new("Private", CHUNK_SIZE * 5 / 2),
+            )
+            .unwrap();
+        let result = ops
+            .handle_chunk_request(
+                &requester,
+                &ChunkRequestPayload {
+                    hash: private,
+                    index: 0,
+                },
+            )
+            .await;
+        assert!(matches!(result, Err(OpsError::AccessDenied)));
+    }
+
+    #[tokio::test]
+    async fn test_download_refetches_corrupt_chunk() {
+        let mut publisher = create_test_ops();
+        let content = large_content();
+        let hash = publish(&mut publisher, &content, 0).await;
+        let manifest = publisher.state.manifests.load(&hash).unwrap().unwrap();
+
+        // The first provider's copy is corrupt in its last chunk
+        let bad = nodalync_net::PeerId::random();
+        let good = nodalync_net::PeerId::random();
+        let mut corrupt = content.clone();
+        corrupt[2 * CHUNK_SIZE as usize + 5] ^= 0xFF;
+        let network = Arc::new(
+            MockNetwork::new()
+                .with_connected_peer(bad)
+                .with_connected_peer(good)
+                .with_chunk_source(bad, hash, corrupt)
+                .with_chunk_source(good, hash, content.clone()),
+        );
+
+        let mut ops = create_test_ops();
+        ops.state.manifests.store(&manifest).unwrap();
+        ops.set_network(Arc::clone(&network) as Arc<dyn Network>);
+
+        let response = ops.download_chunked(&hash).await.unwrap();
+        assert_eq!(response.content, content);
+        assert!(ops.is_content_cached(&hash));
+
+        // Only the corrupt chunk was fetched twice, the second time from
+        // the good provider
+        let requests = network.chunk_requests();
+        assert_eq!(requests.len(), 4);
+        let last = requests
+            .iter()
+            .filter(|(_, r)| r.index == 2)
+            .collect::<Vec<_>>();
+        assert_eq!(last.len(), 2);
+        assert_eq!(last[0].0, bad);
+        assert_eq!(last[1].0, good);
+    }
+
+    #[tokio::test]
+    async fn test_download_fails_without_good_provider() {
+        let mut publisher = create_test_ops();
+        let content = large_content();
+        let hash = publish(&mut publisher, &content, 0).await;
+        let manifest = publisher.state.manifests.load(&hash).unwrap().unwrap();
+
+        let bad = nodalync_net::PeerId::random();
+        let mut corrupt = content.clone();
+        corrupt[0] ^= 0xFF;
+        let network = MockNetwork::new()
+            .with_connected_peer(bad)
+            .with_chunk_source(bad, hash, corrupt);
+
+        let mut ops = create_test_ops();
+        ops.state.manifests.store(&manifest).unwrap();
+        ops.set_network(Arc::new(network) as Arc<dyn Network>);
+
+        let result = ops.download_chunked(&hash).await;
+        assert!(matches!(
+            result,
+            Err(OpsError::ChunkUnavailable { index: 0, .. })
+        ));
+        assert!(!ops.is_content_cached(&hash));
+    }
+
+    #[tokio::test]
+    async fn test_download_refetches_forged_tree() {
+        let mut publisher = create_test_ops();
+        let content = large_content();
+        let hash = publish(&mut publisher, &content, 0).await;
+        let manifest = publisher.state.manifests.load(&hash).unwrap().unwrap();
+
+        // Our copy of the manifest has a forged chunk tree, which the only
+        // (honest) provider disagrees with; the provider's own manifest
+        // has the real one
+        let mut forged = manifest.clone();
+        forged.metadata.chunks.as_mut().unwrap().hashes[0] = content_hash(b"forged");
+        let provider = nodalync_net::PeerId::random();
+        let network = Arc::new(
+            MockNetwork::new()
+                .with_connected_peer(provider)
+                .with_peer_mapping(provider, manifest.owner)
+                .with_chunk_source(provider, hash, content.clone())
+                .with_preview_response(
+                    hash,
+                    PreviewResponsePayload {
+                        hash,
+                        manifest: manifest.clone(),
+                        l1_summary: L1Summary::empty(hash),
+                        collection: None,
+                        forks: Vec::new(),
+                        canonical: None,
+                        window_queries: None,
+                    },
+                ),
+        );
+
+        let mut ops = create_test_ops();
+        ops.state.manifests.store(&forged).unwrap();
+        ops.set_network(Arc::clone(&network) as Arc<dyn Network>);
+
+        let response = ops.download_chunked(&hash).await.unwrap();
+        assert_eq!(response.content, content);
+        assert_eq!(response.manifest.metadata.chunks, manifest.metadata.chunks);
+        let stored = ops.state.manifests.load(&hash).unwrap().unwrap();
+        assert_eq!(stored.metadata.chunks, manifest.metadata.chunks);
+
+        // The provider was kept and asked for every chunk again
+        assert_eq!(network.chunk_requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_download_skips_providers_without_chunked_transfer() {
+        let mut publisher = create_test_ops();
+        let content = large_content();
+        let hash = publish(&mut publisher, &content, 0).await;
+        let manifest = publisher.state.manifests.load(&hash).unwrap().unwrap();
+
+        // The legacy provider advertised capabilities without chunked
+        // transfers; the other never advertised any
+        let legacy = nodalync_net::PeerId::random();
+        let other = nodalync_net::PeerId::random();
+        let (_, legacy_key) = generate_identity();
+        let legacy_peer = peer_id_from_public_key(&legacy_key);
+        let network = Arc::new(
+            MockNetwork::new()
+                .with_connected_peer(legacy)
+                .with_connected_peer(other)
+                .with_peer_mapping(legacy, legacy_peer)
+                .with_chunk_source(legacy, hash, content.clone())
+                .with_chunk_source(other, hash, content.clone()),
+        );
+
+        let mut ops = create_test_ops();
+        ops.state.manifests.store(&manifest).unwrap();
+        ops.state
+            .peers
+            .upsert(
+                &PeerInfo::new(legacy_peer, legacy_key, vec![], 0)
+                    .with_capabilities(vec![Capability::Query]),
+            )
+            .unwrap();
+        ops.set_network(Arc::clone(&network) as Arc<dyn Network>);
+
+        let response = ops.download_chunked(&hash).await.unwrap();
+        assert_eq!(response.content, content);
+        let requests = network.chunk_requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|(peer, _)| *peer == other));
+    }
+}
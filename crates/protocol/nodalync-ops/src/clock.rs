@@ -0,0 +1,381 @@
+//! Clock skew detection and correction.
+//!
+//! Message timestamps must be within `MAX_CLOCK_SKEW_MS` of the receiver's
+//! clock, so a node whose clock drifts has its messages rejected, and
+//! rejects everyone else's. [`probe_clock_skew`](NodeOperations::probe_clock_skew)
+//! pings connected peers and takes each pong's timestamp as the peer's
+//! clock halfway through the round trip. The median across peers, so that
+//! a few wrong (or lying) clocks don't count, is folded into a smoothed
+//! offset. With [`ClockSkewConfig::correct`](crate::ClockSkewConfig::correct)
+//! set, the offset is added to outgoing message timestamps and to the clock
+//! incoming messages are checked against ([`NodeOperations::network_time`]).
+//!
+//! Pongs report the responder's unadjusted clock, so one node's correction
+//! never feeds into another's estimate.
+
+use std::time::Duration;
+
+use futures::stream::{self, StreamExt};
+use nodalync_crypto::Timestamp;
+use nodalync_valid::Validator;
+use nodalync_wire::PingPayload;
+use tracing::{debug, warn};
+
+use crate::error::{OpsError, OpsResult};
+use crate::events::OpsEvent;
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, lock, NodeOperations};
+
+/// Each probe moves the smoothed offset `1/SMOOTHING` of the way towards
+/// the probe's median.
+const SMOOTHING: i64 = 4;
+
+/// The smoothed clock offset.
+#[derive(Debug, Default)]
+pub(crate) struct ClockSkew {
+    /// Milliseconds the network's clock is ahead of ours, once measured.
+    offset_ms: Option<i64>,
+}
+
+impl ClockSkew {
+    /// Fold a probe's median into the offset. The first probe is taken
+    /// as is.
+    fn update(&mut self, median_ms: i64) -> i64 {
+        let offset = match self.offset_ms {
+            None => median_ms,
+            Some(offset) => offset + (median_ms - offset) / SMOOTHING,
+        };
+        self.offset_ms = Some(offset);
+        offset
+    }
+
+    /// Set the offset outright, bypassing smoothing.
+    pub(crate) fn pin(&mut self, offset_ms: i64) {
+        self.offset_ms = Some(offset_ms);
+    }
+}
+
+/// Outcome of one clock skew probe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClockSkewProbe {
+    /// Number of peers pinged.
+    pub pinged: usize,
+    /// Offset measured from each peer that answered (peer clock minus ours,
+    /// in milliseconds).
+    pub samples: Vec<(nodalync_net::PeerId, i64)>,
+    /// Median of the samples, if enough peers answered to count.
+    pub median_ms: Option<i64>,
+    /// Smoothed offset after the probe, in milliseconds.
+    pub offset_ms: i64,
+    /// Whether the offset is beyond the configured tolerance.
+    pub exceeds_tolerance: bool,
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Estimated milliseconds the network's clock is ahead of ours
+    /// (negative when behind, 0 until measured).
+    pub fn clock_offset(&self) -> i64 {
+        lock(&self.clock_skew).offset_ms.unwrap_or(0)
+    }
+
+    /// The current time, corrected by the estimated clock offset when
+    /// correction is enabled.
+    pub fn network_time(&self) -> Timestamp {
+        let now = current_timestamp();
+        if self.config.clock.correct {
+            now.saturating_add_signed(self.clock_offset())
+        } else {
+            now
+        }
+    }
+
+    /// Ping connected peers and update the clock offset from their pongs.
+    ///
+    /// Probes answered by fewer than `min_peers` peers leave the offset
+    /// alone. An offset beyond `tolerance_ms` is logged as a warning and
+    /// reported as `OpsEvent::ClockSkewExceeded` after every probe.
+    pub async fn probe_clock_skew(&self) -> OpsResult<ClockSkewProbe> {
+        let network = self
+            .network()
+            .cloned()
+            .ok_or_else(|| OpsError::invalid_operation("network required to probe clock skew"))?;
+        let config = self.config.clock.clone();
+
+        let peers: Vec<_> = network
+            .connected_peers()
+            .into_iter()
+            .take(config.max_peers)
+            .collect();
+        let pinged = peers.len();
+        let timeout = Duration::from_millis(config.ping_timeout_ms);
+        let mut samples: Vec<_> = stream::iter(peers)
+            .map(|peer| {
+                let network = network.clone();
+                async move {
+                    let nonce = rand::random();
+                    let sent = current_timestamp();
+                    let pong = tokio::time::timeout(
+                        timeout,
+                        network.send_ping(peer, PingPayload { nonce }),
+                    )
+                    .await;
+                    let received = current_timestamp();
+                    match pong {
+                        // Peers that don't report their clock can't be used
+                        Ok(Ok(pong)) if pong.nonce == nonce && pong.timestamp != 0 => {
+                            let midpoint = sent + received.saturating_sub(sent) / 2;
+                            Some((peer, pong.timestamp as i64 - midpoint as i64))
+                        }
+                        Ok(Ok(_)) => None,
+                        Ok(Err(e)) => {
+                            debug!(peer = %peer, error = %e, "Ping failed");
+                            None
+                        }
+                        Err(_) => {
+                            debug!(peer = %peer, "Ping timed out");
+                            None
+                        }
+                    }
+                }
+            })
+            .buffer_unordered(pinged.max(1))
+            .filter_map(|sample| async move { sample })
+            .collect()
+            .await;
+        samples.sort_by_key(|(_, offset)| *offset);
+
+        let median_ms = (!samples.is_empty() && samples.len() >= config.min_peers).then(|| {
+            let n = samples.len();
+            (samples[(n - 1) / 2].1 + samples[n / 2].1) / 2
+        });
+        let offset_ms = match median_ms {
+            Some(median) => {
+                let offset = lock(&self.clock_skew).update(median);
+                debug!(
+                    peers = samples.len(),
+                    median_ms = median,
+                    offset_ms = offset,
+                    "Clock skew probed"
+                );
+                if config.correct {
+                    network.set_clock_offset(offset);
+                }
+                offset
+            }
+            None => {
+                debug!(
+                    answered = samples.len(),
+                    required = config.min_peers,
+                    "Too few peers answered to estimate clock skew"
+                );
+                self.clock_offset()
+            }
+        };
+
+        let exceeds_tolerance = offset_ms.unsigned_abs() > config.tolerance_ms;
+        if exceeds_tolerance {
+            warn!(
+                offset_ms,
+                tolerance_ms = config.tolerance_ms,
+                corrected = config.correct,
+                "LOCAL CLOCK IS {}ms {} THE NETWORK - check the system clock (NTP)",
+                offset_ms.unsigned_abs(),
+                if offset_ms > 0 { "BEHIND" } else { "AHEAD OF" }
+            );
+            self.emit(OpsEvent::ClockSkewExceeded {
+                offset_ms,
+                tolerance_ms: config.tolerance_ms,
+            });
+        }
+
+        Ok(ClockSkewProbe {
+            pinged,
+            samples,
+            median_ms,
+            offset_ms,
+            exceeds_tolerance,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ClockSkewConfig, OpsConfig};
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_test_utils::MockNetwork;
+    use nodalync_types::{Metadata, Visibility};
+    use nodalync_wire::{
+        create_message, decode_payload, encode_message, encode_payload, MessageType, PongPayload,
+        PreviewRequestPayload,
+    };
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn create_test_ops(clock: ClockSkewConfig) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (_, public_key) = generate_identity();
+        let config = OpsConfig::default().with_clock(clock);
+        let ops =
+            DefaultNodeOperations::with_config(state, peer_id_from_public_key(&public_key), config);
+        (ops, temp_dir)
+    }
+
+    /// A mock network whose peers' clocks are the given offsets ahead.
+    fn network_with_offsets(offsets: &[i64]) -> MockNetwork {
+        offsets.iter().fold(MockNetwork::new(), |network, &offset| {
+            let peer = nodalync_net::PeerId::random();
+            network
+                .with_connected_peer(peer)
+                .with_peer_clock_offset(peer, offset)
+        })
+    }
+
+    /// Allowance for the time a mock ping takes.
+    fn assert_near(actual: i64, expected: i64) {
+        assert!(
+            (actual - expected).abs() < 1_000,
+            "{actual} not near {expected}"
+        );
+    }
+
+    #[test]
+    fn test_clock_skew_smoothing() {
+        let mut skew = ClockSkew::default();
+        assert_eq!(skew.update(40_000), 40_000);
+        assert_eq!(skew.update(0), 30_000);
+        assert_eq!(skew.update(30_000), 30_000);
+    }
+
+    #[tokio::test]
+    async fn test_probe_clock_skew() {
+        let (mut ops, _temp) = create_test_ops(ClockSkewConfig::default());
+        // One peer's clock is wildly off; the median ignores it
+        let network = network_with_offsets(&[60_000, 61_000, 62_000, 3_600_000]);
+        ops.set_network(Arc::new(network.clone()));
+        let mut events = ops.subscribe_events();
+
+        let probe = ops.probe_clock_skew().await.unwrap();
+        assert_eq!(probe.pinged, 4);
+        assert_eq!(probe.samples.len(), 4);
+        assert_near(probe.median_ms.unwrap(), 61_500);
+        assert_eq!(probe.offset_ms, probe.median_ms.unwrap());
+        assert!(probe.exceeds_tolerance);
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            OpsEvent::ClockSkewExceeded {
+                tolerance_ms: 30_000,
+                ..
+            }
+        ));
+
+        // The offset corrects outgoing messages and our view of the time
+        assert_eq!(network.clock_offset(), probe.offset_ms);
+        assert_near(
+            ops.network_time() as i64 - current_timestamp() as i64,
+            61_500,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_probe_clock_skew_needs_enough_peers() {
+        let (mut ops, _temp) = create_test_ops(ClockSkewConfig::default());
+        let network = network_with_offsets(&[600_000])
+            // Connected, but never answers
+            .with_connected_peer(nodalync_net::PeerId::random());
+        ops.set_network(Arc::new(network.clone()));
+
+        let probe = ops.probe_clock_skew().await.unwrap();
+        assert_eq!((probe.pinged, probe.samples.len()), (2, 1));
+        assert_eq!(probe.median_ms, None);
+        assert_eq!(probe.offset_ms, 0);
+        assert!(!probe.exceeds_tolerance);
+        assert_eq!(network.clock_offset(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_measure_without_correcting() {
+        let (mut ops, _temp) = create_test_ops(
+            ClockSkewConfig::default()
+                .with_correct(false)
+                .with_peers(1, 8),
+        );
+        let network = network_with_offsets(&[-120_000]);
+        ops.set_network(Arc::new(network.clone()));
+
+        let probe = ops.probe_clock_skew().await.unwrap();
+        assert_near(probe.offset_ms, -120_000);
+        assert!(probe.exceeds_tolerance);
+        assert_eq!(network.clock_offset(), 0);
+        assert_near(ops.network_time() as i64 - current_timestamp() as i64, 0);
+    }
+
+    #[tokio::test]
+    async fn test_inbound_timestamps_checked_against_network_time() {
+        let (mut ops, _temp) = create_test_ops(ClockSkewConfig::default().with_peers(1, 8));
+        let content = b"Free notes";
+        let hash = ops
+            .create_content(content, Metadata::new("Notes", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 0)
+            .await
+            .unwrap();
+        let (private_key, public_key) = generate_identity();
+        let sender = peer_id_from_public_key(&public_key);
+        let libp2p_peer = nodalync_net::PeerId::random();
+        let request = |message_type, payload: Vec<u8>, timestamp| {
+            encode_message(&create_message(
+                message_type,
+                payload,
+                sender,
+                timestamp,
+                &private_key,
+            ))
+            .unwrap()
+        };
+        let preview = encode_payload(&PreviewRequestPayload { hash }).unwrap();
+        let ping = encode_payload(&PingPayload { nonce: 7 }).unwrap();
+
+        // Ten minutes ahead of our clock: outside MAX_CLOCK_SKEW_MS
+        let ahead = current_timestamp() + 600_000;
+        let response = ops
+            .handle_inbound_request(
+                &libp2p_peer,
+                &request(MessageType::PreviewRequest, preview.clone(), ahead),
+            )
+            .await
+            .unwrap();
+        assert!(response.is_none());
+
+        // Pings are answered anyway, so a skewed peer can measure its skew,
+        // with our unadjusted clock
+        let (message_type, payload) = ops
+            .handle_inbound_request(&libp2p_peer, &request(MessageType::Ping, ping, ahead))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(message_type, MessageType::Pong);
+        let pong: PongPayload = decode_payload(&payload).unwrap();
+        assert_eq!(pong.nonce, 7);
+        assert_near(pong.timestamp as i64, current_timestamp() as i64);
+
+        // Once the network agrees our clock is behind, the message is on time
+        ops.set_network(Arc::new(network_with_offsets(&[600_000])));
+        ops.probe_clock_skew().await.unwrap();
+        let response = ops
+            .handle_inbound_request(
+                &libp2p_peer,
+                &request(MessageType::PreviewRequest, preview, ahead),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(response, Some((MessageType::PreviewResponse, _))));
+    }
+}
@@ -0,0 +1,272 @@
+//! Batched cooperative channel close.
+//!
+//! Closing channels one by one waits out each unresponsive peer in turn,
+//! which is slow when a node has many channels (e.g. on MCP shutdown). This
+//! module closes all open channels in three phases:
+//!
+//! 1. Sign and record every pending close (sequential, local only).
+//! 2. Exchange close signatures with up to `max_concurrent` peers at a time,
+//!    each bounded by `peer_timeout_ms`.
+//! 3. Submit the cooperative closes and mark channels closed (sequential),
+//!    then settle all queued distributions in one batch.
+//!
+//! Progress is reported on the operations event bus.
+
+use std::time::Duration;
+
+use futures::stream::{self, StreamExt};
+use nodalync_crypto::{Hash, PeerId, PrivateKey};
+use nodalync_store::ChannelStore;
+use nodalync_valid::Validator;
+
+use crate::channel::request_close_ack;
+use crate::error::{CloseResult, OpsResult};
+use crate::events::{ChannelCloseStatus, OpsEvent};
+use crate::extraction::L1Extractor;
+use crate::node_ops::NodeOperations;
+
+/// Outcome of closing all open channels.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CloseBatchReport {
+    /// Channels closed cooperatively.
+    pub closed: Vec<PeerId>,
+    /// Channels whose peer did not acknowledge in time. They keep their
+    /// pending close and can be disputed.
+    pub unresponsive: Vec<PeerId>,
+    /// Channels that could not be closed, with the reason.
+    pub failed: Vec<(PeerId, String)>,
+    /// Settlement batch created for queued distributions, if any.
+    pub settlement_batch: Option<Hash>,
+}
+
+impl CloseBatchReport {
+    /// Number of channels processed.
+    pub fn total(&self) -> usize {
+        self.closed.len() + self.unresponsive.len() + self.failed.len()
+    }
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Cooperatively close every open channel.
+    ///
+    /// Peers are contacted concurrently per `OpsConfig::close_batch`; one
+    /// settlement batch is submitted for everything queued at the end. A
+    /// failed settlement is logged and leaves the queue for the next run.
+    pub async fn close_all_payment_channels(
+        &self,
+        private_key: &PrivateKey,
+    ) -> OpsResult<CloseBatchReport> {
+        let channels = self.state.channels.list_open()?;
+        let total = channels.len();
+        let mut report = CloseBatchReport::default();
+        let mut completed = 0;
+
+        self.emit(OpsEvent::ChannelCloseStarted { total });
+
+        // 1. Sign and record pending closes
+        let mut started = Vec::with_capacity(total);
+        for (peer, _) in channels {
+            match self.begin_channel_close(&peer, private_key) {
+                Ok((channel, payload)) => started.push((peer, channel, payload)),
+                Err(e) => {
+                    completed += 1;
+                    report.failed.push((peer, e.to_string()));
+                    self.emit(OpsEvent::ChannelCloseProgress {
+                        peer,
+                        status: ChannelCloseStatus::Failed,
+                        completed,
+                        total,
+                    });
+                }
+            }
+        }
+
+        // 2. Exchange signatures concurrently
+        let network = self.network().cloned();
+        let timeout = Duration::from_millis(self.config.close_batch.peer_timeout_ms);
+        let acks: Vec<_> = stream::iter(started)
+            .map(|(peer, channel, payload)| {
+                let network = network.clone();
+                async move {
+                    let signature =
+                        tokio::time::timeout(timeout, request_close_ack(network, &peer, payload))
+                            .await
+                            .ok()
+                            .flatten();
+                    (peer, channel, signature)
+                }
+            })
+            .buffer_unordered(self.config.close_batch.max_concurrent.max(1))
+            .collect()
+            .await;
+
+        // 3. Submit closes and update channel state
+        for (peer, channel, signature) in acks {
+            let status = match self.finish_channel_close(&peer, channel, signature).await {
+                Ok(result) if result.is_success() => {
+                    report.closed.push(peer);
+                    ChannelCloseStatus::Closed
+                }
+                Ok(CloseResult::PeerUnresponsive { .. }) => {
+                    report.unresponsive.push(peer);
+                    ChannelCloseStatus::Unresponsive
+                }
+                Ok(CloseResult::OnChainFailed { error }) => {
+                    report.failed.push((peer, error));
+                    ChannelCloseStatus::Failed
+                }
+                Ok(_) => unreachable!("successful closes handled above"),
+                Err(e) => {
+                    report.failed.push((peer, e.to_string()));
+                    ChannelCloseStatus::Failed
+                }
+            };
+            completed += 1;
+            self.emit(OpsEvent::ChannelCloseProgress {
+                peer,
+                status,
+                completed,
+                total,
+            });
+        }
+
+        // Settle everything queued in a single batch
+        match self.force_settlement().await {
+            Ok(Some(batch_id)) => {
+                report.settlement_batch = Some(batch_id);
+                self.emit(OpsEvent::SettlementBatch { batch_id });
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!(error = %e, "Settlement after channel close batch failed");
+            }
+        }
+
+        self.emit(OpsEvent::ChannelCloseFinished {
+            closed: report.closed.len(),
+            unresponsive: report.unresponsive.len(),
+            failed: report.failed.len(),
+        });
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeStateConfig, QueuedDistribution, SettlementQueueStore};
+    use nodalync_types::Channel;
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let config = NodeStateConfig::new(temp_dir.path());
+        let state = nodalync_store::NodeState::open(config).unwrap();
+
+        let (_, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+
+        let ops = DefaultNodeOperations::with_defaults(state, peer_id);
+        (ops, temp_dir)
+    }
+
+    fn open_channel(ops: &mut DefaultNodeOperations, seed: &[u8]) -> PeerId {
+        let (_, public_key) = generate_identity();
+        let peer = peer_id_from_public_key(&public_key);
+        let channel = Channel::accepted(content_hash(seed), peer, 100, 100, 1000);
+        ops.state.channels.create(&peer, channel).unwrap();
+        peer
+    }
+
+    #[tokio::test]
+    async fn test_close_all_without_network() {
+        let (mut ops, _temp) = create_test_ops();
+        let (private_key, _) = generate_identity();
+        let peers = [open_channel(&mut ops, b"a"), open_channel(&mut ops, b"b")];
+        let mut events = ops.subscribe_events();
+
+        let report = ops.close_all_payment_channels(&private_key).await.unwrap();
+
+        // Nobody can acknowledge without a network
+        assert_eq!(report.total(), 2);
+        assert_eq!(report.unresponsive.len(), 2);
+        assert!(report.settlement_batch.is_none());
+        for peer in &peers {
+            let channel = ops.state.channels.get(peer).unwrap().unwrap();
+            assert!(channel.pending_close.is_some());
+        }
+
+        assert_eq!(
+            events.try_recv().unwrap(),
+            OpsEvent::ChannelCloseStarted { total: 2 }
+        );
+        for completed in 1..=2 {
+            match events.try_recv().unwrap() {
+                OpsEvent::ChannelCloseProgress {
+                    status,
+                    completed: c,
+                    total,
+                    ..
+                } => {
+                    assert_eq!(status, ChannelCloseStatus::Unresponsive);
+                    assert_eq!((c, total), (completed, 2));
+                }
+                other => panic!("unexpected event: {:?}", other),
+            }
+        }
+        assert_eq!(
+            events.try_recv().unwrap(),
+            OpsEvent::ChannelCloseFinished {
+                closed: 0,
+                unresponsive: 2,
+                failed: 0,
+            }
+        );
+
+        // A second run can't start closes that are already pending
+        let report = ops.close_all_payment_channels(&private_key).await.unwrap();
+        assert_eq!(report.failed.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_close_all_settles_queue_once() {
+        let (ops, _temp) = create_test_ops();
+        let (private_key, _) = generate_identity();
+        let (_, public_key) = generate_identity();
+        let recipient = peer_id_from_public_key(&public_key);
+        for i in 0..3u8 {
+            ops.state
+                .settlement
+                .enqueue(QueuedDistribution::new(
+                    content_hash(&[i]),
+                    recipient,
+                    10,
+                    content_hash(b"source"),
+                    1000,
+                ))
+                .unwrap();
+        }
+        let mut events = ops.subscribe_events();
+
+        let report = ops.close_all_payment_channels(&private_key).await.unwrap();
+        assert_eq!(report.total(), 0);
+        let batch_id = report.settlement_batch.unwrap();
+        assert_eq!(ops.get_pending_settlement_total().unwrap(), 0);
+
+        assert_eq!(
+            events.try_recv().unwrap(),
+            OpsEvent::ChannelCloseStarted { total: 0 }
+        );
+        assert_eq!(
+            events.try_recv().unwrap(),
+            OpsEvent::SettlementBatch { batch_id }
+        );
+    }
+}
@@ -0,0 +1,389 @@
+//! Collection operations.
+//!
+//! A collection is a curated, ordered list of the node's own content, stored
+//! as JSON under a `ContentType::Collection` manifest. Its provenance is
+//! derived from the items with each item's weight applied, so a paid query
+//! for the collection (a bundle purchase) goes through the normal 95/5
+//! settlement and pays the items' roots in proportion to their weights.
+//! The buyer receives every item's content along with the list.
+
+use nodalync_crypto::{content_hash, Hash, Timestamp};
+use nodalync_econ::validate_price;
+use nodalync_store::{CacheStore, CachedContent, ContentStore, ManifestStore, ProvenanceGraph};
+use nodalync_types::{
+    Collection, ContentType, Economics, Manifest, Metadata, Provenance, Version, Visibility,
+};
+use nodalync_valid::{validate_collection, ValidationError, Validator};
+use nodalync_wire::{BundleItem, PaymentReceipt};
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::helpers::verify_content_hash;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+/// MIME type of stored collection content.
+pub const COLLECTION_MIME_TYPE: &str = "application/vnd.nodalync.collection+json";
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Create a collection of local content.
+    ///
+    /// 1. Loads item manifests (items must be owned by this node)
+    /// 2. Builds provenance weighted by item weight
+    /// 3. Creates a Private manifest priced at the bundle price
+    /// 4. Validates the collection
+    /// 5. Stores content, manifest and provenance edges
+    pub fn create_collection(&self, collection: Collection) -> OpsResult<Hash> {
+        let timestamp = current_timestamp();
+        self.create_collection_with_timestamp(collection, timestamp)
+    }
+
+    /// Create a collection with a specific timestamp (for testing).
+    pub fn create_collection_with_timestamp(
+        &self,
+        collection: Collection,
+        timestamp: Timestamp,
+    ) -> OpsResult<Hash> {
+        // 1. Load item manifests
+        let mut items = Vec::with_capacity(collection.items.len());
+        for item in &collection.items {
+            let manifest = self
+                .state
+                .manifests
+                .load(&item.hash)?
+                .ok_or(OpsError::ManifestNotFound(item.hash))?;
+            if manifest.owner != self.peer_id() {
+                return Err(OpsError::AccessDenied);
+            }
+            items.push(manifest);
+        }
+
+        if let Some(price) = collection.bundle_price.filter(|p| *p > 0) {
+            validate_price(price)?;
+        }
+
+        // 2. Build weighted provenance (duplicates are rejected by validation)
+        let provenance_sources: Vec<_> = collection
+            .items
+            .iter()
+            .filter_map(|item| {
+                items
+                    .iter()
+                    .find(|m| m.hash == item.hash)
+                    .map(|m| (m.hash, &m.provenance, m.owner, m.visibility, item.weight))
+            })
+            .collect();
+        let provenance = Provenance::from_weighted_sources(&provenance_sources);
+
+        let content = serde_json::to_vec(&collection).map_err(|e| {
+            OpsError::invalid_operation(format!("failed to serialize collection: {}", e))
+        })?;
+        let hash = content_hash(&content);
+
+        // 3. Create manifest
+        let mut metadata = Metadata::new(&collection.title, content.len() as u64)
+            .with_mime_type(COLLECTION_MIME_TYPE);
+        if let Some(description) = &collection.description {
+            metadata = metadata.with_description(description);
+        }
+        let manifest = Manifest {
+            hash,
+            content_type: ContentType::Collection,
+            owner: self.peer_id(),
+            version: Version::new_v1(hash, timestamp),
+            visibility: Visibility::Private,
+            access: Default::default(),
+            metadata,
+            economics: Economics::with_price(collection.price()),
+            provenance,
+            created_at: timestamp,
+            updated_at: timestamp,
+        };
+
+        // 4. Validate
+        validate_collection(&collection, &manifest, &items)?;
+        self.validator.validate_content(&content, &manifest)?;
+        self.validate_metadata_fields(&manifest.metadata)?;
+
+        // 5. Store
+        self.state.content.store_verified(&hash, &content)?;
+        self.state.manifests.store(&manifest)?;
+        self.state.provenance.add(&hash, &collection.hashes())?;
+
+        Ok(hash)
+    }
+
+    /// Load a collection's item list.
+    ///
+    /// Works for local collections and for collections retrieved by a query.
+    pub fn get_collection(&self, hash: &Hash) -> OpsResult<Collection> {
+        let manifest = self
+            .state
+            .manifests
+            .load(hash)?
+            .ok_or(OpsError::ManifestNotFound(*hash))?;
+        if manifest.content_type != ContentType::Collection {
+            return Err(OpsError::invalid_operation(format!(
+                "{} is not a collection",
+                hash
+            )));
+        }
+
+        let content = match self.state.content.load(hash)? {
+            Some(content) => content,
+            None => {
+                self.state
+                    .cache
+                    .get(hash)?
+                    .ok_or(OpsError::NotFound(*hash))?
+                    .content
+            }
+        };
+
+        parse_collection(&content)
+    }
+
+    /// Check that a collection may be published at `price`.
+    ///
+    /// The bundle price is part of the collection's content, so it can't be
+    /// changed without creating a new collection. Items must already be
+    /// published so buyers of a free list can query them.
+    pub(crate) fn check_collection_publish(
+        &self,
+        manifest: &Manifest,
+        price: nodalync_types::Amount,
+    ) -> OpsResult<()> {
+        let collection = self.get_collection(&manifest.hash)?;
+        if price != collection.price() {
+            return Err(ValidationError::CollectionPriceMismatch {
+                expected: collection.price(),
+                actual: price,
+            }
+            .into());
+        }
+
+        for item in &collection.items {
+            let published = self.state.manifests.load(&item.hash)?.is_some_and(|m| {
+                !matches!(m.visibility, Visibility::Private | Visibility::Offline)
+            });
+            if !published {
+                return Err(OpsError::invalid_operation(format!(
+                    "collection item {} is not published",
+                    item.hash
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Load the item contents delivered with a paid collection.
+    pub(crate) fn load_bundle(&self, collection: &Collection) -> OpsResult<Vec<BundleItem>> {
+        let mut bundle = Vec::with_capacity(collection.items.len());
+        for item in &collection.items {
+            let manifest = self
+                .state
+                .manifests
+                .load(&item.hash)?
+                .ok_or(OpsError::ManifestNotFound(item.hash))?;
+            let content = self
+                .state
+                .content
+                .load(&item.hash)?
+                .ok_or(OpsError::NotFound(item.hash))?;
+            bundle.push(BundleItem {
+                hash: item.hash,
+                manifest,
+                content,
+                content_key: None,
+            });
+        }
+        Ok(bundle)
+    }
+
+    /// Cache the items received with a paid collection.
+    ///
+    /// Items that aren't listed in the collection or don't match their hash
+    /// are skipped. Returns the hashes of the cached items.
+    pub(crate) fn cache_bundle(
+        &self,
+        collection_content: &[u8],
+        bundle: Vec<BundleItem>,
+        receipt: &PaymentReceipt,
+        timestamp: Timestamp,
+    ) -> OpsResult<Vec<Hash>> {
+        if bundle.is_empty() {
+            return Ok(Vec::new());
+        }
+        let collection = parse_collection(collection_content)?;
+
+        let mut cached = Vec::with_capacity(bundle.len());
+        let mut manifests = Vec::with_capacity(bundle.len());
+        for mut item in bundle {
+            if !collection.contains(&item.hash)
+                || item.manifest.hash != item.hash
+                || !verify_content_hash(&item.content, &item.hash)
+            {
+                tracing::warn!(hash = %item.hash, "Skipping invalid collection bundle item");
+                continue;
+            }
+            self.check_remote_metadata(&mut item.manifest);
+            if let Err(e) = self.screen_remote_content(&item.manifest) {
+                tracing::warn!(hash = %item.hash, "Skipping collection bundle item: {}", e);
+                continue;
+            }
+
+            self.state.cache.cache(CachedContent::new(
+                item.hash,
+                item.content,
+                item.manifest.owner,
+                timestamp,
+                receipt.clone(),
+            ))?;
+            manifests.push(item.manifest);
+            cached.push(item.hash);
+        }
+        self.state.manifests.store_batch(&manifests)?;
+        Ok(cached)
+    }
+}
+
+/// Parse stored collection content.
+fn parse_collection(content: &[u8]) -> OpsResult<Collection> {
+    serde_json::from_slice(content)
+        .map_err(|e| OpsError::invalid_operation(format!("failed to parse collection: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::NodeStateConfig;
+    use nodalync_types::CollectionItem;
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let config = NodeStateConfig::new(temp_dir.path());
+        let state = nodalync_store::NodeState::open(config).unwrap();
+
+        let (_, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+
+        let ops = DefaultNodeOperations::with_defaults(state, peer_id);
+        (ops, temp_dir)
+    }
+
+    fn create_item(ops: &mut DefaultNodeOperations, content: &[u8]) -> Hash {
+        let metadata = Metadata::new("Item", content.len() as u64);
+        ops.create_content(content, metadata).unwrap()
+    }
+
+    #[test]
+    fn test_create_collection() {
+        let (mut ops, _temp) = create_test_ops();
+        let a = create_item(&mut ops, b"First item");
+        let b = create_item(&mut ops, b"Second item");
+
+        let collection = Collection::new("Reading list")
+            .with_description("Start here")
+            .with_item(CollectionItem::new(a, "First"))
+            .with_item(CollectionItem::new(b, "Second").with_weight(3))
+            .with_bundle_price(400);
+        let hash = ops.create_collection(collection.clone()).unwrap();
+
+        let manifest = ops.state.manifests.load(&hash).unwrap().unwrap();
+        assert_eq!(manifest.content_type, ContentType::Collection);
+        assert_eq!(manifest.visibility, Visibility::Private);
+        assert_eq!(manifest.economics.price, 400);
+        assert_eq!(manifest.provenance.derived_from, vec![a, b]);
+
+        let weight = |h| {
+            manifest
+                .provenance
+                .root_l0l1
+                .iter()
+                .find(|e| e.hash == h)
+                .unwrap()
+                .weight
+        };
+        assert_eq!(weight(b), 3 * weight(a));
+
+        assert_eq!(ops.get_collection(&hash).unwrap(), collection);
+        assert_eq!(ops.load_bundle(&collection).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_create_collection_rejects_invalid_items() {
+        let (mut ops, _temp) = create_test_ops();
+        let a = create_item(&mut ops, b"First item");
+
+        // Unknown item
+        let missing = content_hash(b"missing");
+        let result = ops.create_collection(
+            Collection::new("List").with_item(CollectionItem::new(missing, "Missing")),
+        );
+        assert!(matches!(result, Err(OpsError::ManifestNotFound(_))));
+
+        // Empty collection
+        let result = ops.create_collection(Collection::new("Empty"));
+        assert!(matches!(
+            result,
+            Err(OpsError::Validation(ValidationError::CollectionEmpty))
+        ));
+
+        // Nested collection
+        let inner = ops
+            .create_collection(Collection::new("Inner").with_item(CollectionItem::new(a, "A")))
+            .unwrap();
+        let result = ops.create_collection(
+            Collection::new("Outer").with_item(CollectionItem::new(inner, "Inner")),
+        );
+        assert!(matches!(
+            result,
+            Err(OpsError::Validation(
+                ValidationError::CollectionInvalidItemType { .. }
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_publish_collection_requires_published_items() {
+        let (mut ops, _temp) = create_test_ops();
+        let a = create_item(&mut ops, b"First item");
+        let hash = ops
+            .create_collection(
+                Collection::new("List")
+                    .with_item(CollectionItem::new(a, "A"))
+                    .with_bundle_price(100),
+            )
+            .unwrap();
+
+        // Item still private
+        let result = ops.publish_content(&hash, Visibility::Shared, 100).await;
+        assert!(matches!(result, Err(OpsError::InvalidOperation(_))));
+
+        ops.publish_content(&a, Visibility::Shared, 10)
+            .await
+            .unwrap();
+
+        // Price must match the bundle price
+        let result = ops.publish_content(&hash, Visibility::Shared, 50).await;
+        assert!(matches!(
+            result,
+            Err(OpsError::Validation(
+                ValidationError::CollectionPriceMismatch { .. }
+            ))
+        ));
+
+        ops.publish_content(&hash, Visibility::Shared, 100)
+            .await
+            .unwrap();
+        let manifest = ops.state.manifests.load(&hash).unwrap().unwrap();
+        assert_eq!(manifest.visibility, Visibility::Shared);
+    }
+}
@@ -3,10 +3,22 @@
 //! This module defines configuration structures for channel management
 //! and operations behavior.
 
-use nodalync_types::Amount;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use nodalync_crypto::{Hash, PeerId};
+use nodalync_econ::AppFee;
+use nodalync_store::{DigestPeriod, RetentionCategory};
+use nodalync_types::{Amount, QuotaPolicy, MAX_SNAPSHOT_ANNOUNCEMENTS, MAX_SNAPSHOT_PEERS};
+use nodalync_valid::BondRequirements;
+use serde::{Deserialize, Serialize};
+
+use crate::trust::TrustCheck;
 
 /// Configuration for payment channel behavior.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ChannelConfig {
     /// Minimum deposit required to open a channel.
     pub min_deposit: Amount,
@@ -29,73 +41,2098 @@ pub struct ChannelConfig {
     pub auto_deposit_cooldown_secs: u64,
 }
 
-impl Default for ChannelConfig {
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self {
+            // 100 HBAR in tinybars
+            min_deposit: 100_0000_0000,
+            // 1000 HBAR in tinybars
+            default_deposit: 1000_0000_0000,
+            // 500 HBAR max accept deposit
+            max_accept_deposit: 500_0000_0000,
+            // Disabled by default for security
+            auto_deposit_on_channel_open: false,
+            // 200 HBAR auto-deposit amount
+            auto_deposit_amount: 200_0000_0000,
+            // 100 HBAR minimum balance threshold
+            auto_deposit_min_balance: 100_0000_0000,
+            // 5 minute cooldown
+            auto_deposit_cooldown_secs: 300,
+        }
+    }
+}
+
+impl ChannelConfig {
+    /// Create a new channel configuration with basic settings.
+    pub fn new(min_deposit: Amount, default_deposit: Amount) -> Self {
+        Self {
+            min_deposit,
+            default_deposit,
+            ..Default::default()
+        }
+    }
+
+    /// Set the maximum deposit to accept when a peer opens a channel.
+    pub fn with_max_accept_deposit(mut self, amount: Amount) -> Self {
+        self.max_accept_deposit = amount;
+        self
+    }
+
+    /// Enable or disable auto-deposit on channel open.
+    pub fn with_auto_deposit(mut self, enabled: bool) -> Self {
+        self.auto_deposit_on_channel_open = enabled;
+        self
+    }
+
+    /// Set the fixed auto-deposit amount.
+    pub fn with_auto_deposit_amount(mut self, amount: Amount) -> Self {
+        self.auto_deposit_amount = amount;
+        self
+    }
+
+    /// Set the minimum balance threshold for auto-deposit.
+    pub fn with_auto_deposit_min_balance(mut self, amount: Amount) -> Self {
+        self.auto_deposit_min_balance = amount;
+        self
+    }
+
+    /// Set the cooldown between auto-deposits in seconds.
+    pub fn with_auto_deposit_cooldown(mut self, secs: u64) -> Self {
+        self.auto_deposit_cooldown_secs = secs;
+        self
+    }
+}
+
+/// Configuration for channel rebalancing.
+///
+/// A channel is skewed when our share of its capacity is above
+/// `skew_threshold` (saturated: the peer can't pay us) or below
+/// `1 - skew_threshold` (depleted: we can't pay the peer).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RebalanceConfig {
+    /// Perform close/reopen actions automatically instead of only suggesting them.
+    /// Default: false (opt-in, since reopening locks a new deposit).
+    pub auto_rebalance: bool,
+    /// Fraction of channel capacity on one side that counts as skewed.
+    pub skew_threshold: f64,
+    /// Smallest amount worth moving in a circular payment.
+    pub min_amount: Amount,
+}
+
+impl Default for RebalanceConfig {
+    fn default() -> Self {
+        Self {
+            auto_rebalance: false,
+            skew_threshold: 0.8,
+            // 10 HBAR in tinybars
+            min_amount: 10_0000_0000,
+        }
+    }
+}
+
+impl RebalanceConfig {
+    /// Enable or disable automatic rebalancing.
+    pub fn with_auto_rebalance(mut self, enabled: bool) -> Self {
+        self.auto_rebalance = enabled;
+        self
+    }
+
+    /// Set the skew threshold (clamped to 0.5..=1.0).
+    pub fn with_skew_threshold(mut self, threshold: f64) -> Self {
+        self.skew_threshold = threshold.clamp(0.5, 1.0);
+        self
+    }
+
+    /// Set the smallest amount worth moving.
+    pub fn with_min_amount(mut self, amount: Amount) -> Self {
+        self.min_amount = amount;
+        self
+    }
+}
+
+/// Configuration for automatically topping up the settlement balance.
+///
+/// When enabled, the balance is checked every `check_interval_secs` and
+/// `amount` is deposited whenever it is below `min_balance`, so queries
+/// don't start failing mid-session. Top-ups in any 24 hours never exceed
+/// `daily_cap`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TopUpConfig {
+    /// Whether the settlement balance is topped up automatically.
+    /// Default: false (opt-in, since it moves funds without asking).
+    pub enabled: bool,
+    /// Balance below which a top-up is made.
+    pub min_balance: Amount,
+    /// Amount deposited per top-up.
+    pub amount: Amount,
+    /// Maximum total of top-ups in any 24 hours.
+    pub daily_cap: Amount,
+    /// How often the balance is checked, in seconds.
+    pub check_interval_secs: u64,
+}
+
+impl Default for TopUpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            // 10 HBAR in tinybars
+            min_balance: 10_0000_0000,
+            // 50 HBAR in tinybars
+            amount: 50_0000_0000,
+            // 200 HBAR in tinybars
+            daily_cap: 200_0000_0000,
+            check_interval_secs: 60,
+        }
+    }
+}
+
+impl TopUpConfig {
+    /// Enable or disable automatic top-ups.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Set the balance below which a top-up is made.
+    pub fn with_min_balance(mut self, amount: Amount) -> Self {
+        self.min_balance = amount;
+        self
+    }
+
+    /// Set the amount deposited per top-up.
+    pub fn with_amount(mut self, amount: Amount) -> Self {
+        self.amount = amount;
+        self
+    }
+
+    /// Set the maximum total of top-ups in any 24 hours.
+    pub fn with_daily_cap(mut self, amount: Amount) -> Self {
+        self.daily_cap = amount;
+        self
+    }
+
+    /// Set how often the balance is checked, in seconds (at least 1).
+    pub fn with_check_interval(mut self, secs: u64) -> Self {
+        self.check_interval_secs = secs.max(1);
+        self
+    }
+}
+
+/// Data retention policy.
+///
+/// Each category of data has its own time-to-live; records older than it
+/// are deleted by `enforce_retention`, which the node runs every
+/// `check_interval_secs`. Categories without a TTL are kept forever.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetentionConfig {
+    /// TTL in seconds for each category that expires.
+    /// Default: empty (keep everything).
+    pub ttls: BTreeMap<RetentionCategory, u64>,
+    /// How often the policy is enforced, in seconds.
+    /// Default: 3600.
+    pub check_interval_secs: u64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            ttls: BTreeMap::new(),
+            check_interval_secs: 3600,
+        }
+    }
+}
+
+impl RetentionConfig {
+    /// Set the TTL for a category, in seconds (`None` keeps it forever).
+    pub fn with_ttl(mut self, category: RetentionCategory, ttl_secs: Option<u64>) -> Self {
+        match ttl_secs {
+            Some(ttl) => self.ttls.insert(category, ttl),
+            None => self.ttls.remove(&category),
+        };
+        self
+    }
+
+    /// Set how often the policy is enforced, in seconds (at least 1).
+    pub fn with_check_interval(mut self, secs: u64) -> Self {
+        self.check_interval_secs = secs.max(1);
+        self
+    }
+
+    /// Get the TTL for a category, in seconds.
+    pub fn ttl(&self, category: RetentionCategory) -> Option<u64> {
+        self.ttls.get(&category).copied()
+    }
+}
+
+/// Clock skew detection and correction.
+///
+/// Every `probe_interval_secs` the node pings up to `max_peers` connected
+/// peers and compares their clocks with its own. Once at least `min_peers`
+/// have answered, the median offset is folded into a smoothed estimate
+/// that corrects outgoing message timestamps and the clock incoming ones
+/// are checked against.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClockSkewConfig {
+    /// Whether the estimated offset is applied (it is always measured).
+    /// Default: true.
+    pub correct: bool,
+    /// Offset from the network beyond which the node warns, in milliseconds.
+    /// Default: 30_000.
+    pub tolerance_ms: u64,
+    /// Maximum number of peers pinged per probe.
+    /// Default: 8.
+    pub max_peers: usize,
+    /// Minimum number of answers for a probe to count, so a single peer
+    /// can't move our clock.
+    /// Default: 3.
+    pub min_peers: usize,
+    /// How long to wait for each pong, in milliseconds.
+    /// Default: 5_000.
+    pub ping_timeout_ms: u64,
+    /// How often peers are probed, in seconds.
+    /// Default: 300.
+    pub probe_interval_secs: u64,
+}
+
+impl Default for ClockSkewConfig {
+    fn default() -> Self {
+        Self {
+            correct: true,
+            tolerance_ms: 30_000,
+            max_peers: 8,
+            min_peers: 3,
+            ping_timeout_ms: 5_000,
+            probe_interval_secs: 300,
+        }
+    }
+}
+
+impl ClockSkewConfig {
+    /// Set whether the estimated offset is applied.
+    pub fn with_correct(mut self, correct: bool) -> Self {
+        self.correct = correct;
+        self
+    }
+
+    /// Set the offset beyond which the node warns, in milliseconds.
+    pub fn with_tolerance_ms(mut self, tolerance_ms: u64) -> Self {
+        self.tolerance_ms = tolerance_ms;
+        self
+    }
+
+    /// Set how many peers are pinged per probe, and how many must answer
+    /// (both at least 1).
+    pub fn with_peers(mut self, min_peers: usize, max_peers: usize) -> Self {
+        self.max_peers = max_peers.max(1);
+        self.min_peers = min_peers.clamp(1, self.max_peers);
+        self
+    }
+
+    /// Set the per-peer pong timeout in milliseconds.
+    pub fn with_ping_timeout(mut self, timeout_ms: u64) -> Self {
+        self.ping_timeout_ms = timeout_ms;
+        self
+    }
+
+    /// Set how often peers are probed, in seconds (at least 1).
+    pub fn with_probe_interval(mut self, secs: u64) -> Self {
+        self.probe_interval_secs = secs.max(1);
+        self
+    }
+}
+
+/// Peer latency measurement and QoS-ranked provider selection.
+///
+/// Every `probe_interval_secs` the node pings up to `max_peers` connected
+/// peers it knows and records each round trip, or the lost ping, in the
+/// peer store. Providers of content are tried in order of a blend of
+/// reputation and the peer's QoS score, `weight` being the QoS share.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QosConfig {
+    /// Whether peers are probed periodically.
+    /// Default: true.
+    pub enabled: bool,
+    /// Maximum number of peers pinged per probe.
+    /// Default: 16.
+    pub max_peers: usize,
+    /// How long to wait for each pong before counting it lost, in
+    /// milliseconds.
+    /// Default: 5_000.
+    pub ping_timeout_ms: u64,
+    /// How often peers are probed, in seconds.
+    /// Default: 120.
+    pub probe_interval_secs: u64,
+    /// Share of a provider's rank taken from its QoS score rather than its
+    /// reputation, from 0.0 (reputation only) to 1.0 (QoS only).
+    /// Default: 0.5.
+    pub weight: f64,
+}
+
+impl Default for QosConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_peers: 16,
+            ping_timeout_ms: 5_000,
+            probe_interval_secs: 120,
+            weight: 0.5,
+        }
+    }
+}
+
+impl QosConfig {
+    /// Set whether peers are probed periodically.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Set how many peers are pinged per probe (at least 1).
+    pub fn with_max_peers(mut self, max_peers: usize) -> Self {
+        self.max_peers = max_peers.max(1);
+        self
+    }
+
+    /// Set the per-peer pong timeout in milliseconds.
+    pub fn with_ping_timeout(mut self, timeout_ms: u64) -> Self {
+        self.ping_timeout_ms = timeout_ms;
+        self
+    }
+
+    /// Set how often peers are probed, in seconds (at least 1).
+    pub fn with_probe_interval(mut self, secs: u64) -> Self {
+        self.probe_interval_secs = secs.max(1);
+        self
+    }
+
+    /// Set the QoS share of a provider's rank (clamped to 0.0..=1.0).
+    pub fn with_weight(mut self, weight: f64) -> Self {
+        self.weight = if weight.is_nan() {
+            0.0
+        } else {
+            weight.clamp(0.0, 1.0)
+        };
+        self
+    }
+}
+
+/// Content availability health checks.
+///
+/// Every `check_interval_secs` the node asks a remote peer to dial it at
+/// its listen addresses and preview up to `sample_size` of its shared
+/// content, least recently checked first, and records whether each was
+/// reachable. Checks go to a random `checkers` entry that is connected, or
+/// to a random connected peer when none is. Content that fails
+/// `alert_after` checks in a row is reported as
+/// `OpsEvent::ContentUnreachable`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AvailabilityConfig {
+    /// Whether our content is checked periodically.
+    /// Default: false.
+    pub enabled: bool,
+    /// How often content is checked, in seconds.
+    /// Default: 3_600.
+    pub check_interval_secs: u64,
+    /// Most content checked per run.
+    /// Default: 3.
+    pub sample_size: usize,
+    /// Peers preferred as checkers, such as a checker service.
+    /// Default: empty (any connected peer).
+    pub checkers: Vec<PeerId>,
+    /// How long to wait for each result, in milliseconds. Covers the
+    /// checker's dial and preview.
+    /// Default: 30_000.
+    pub timeout_ms: u64,
+    /// Consecutive failed checks of a hash before it is reported.
+    /// Default: 2.
+    pub alert_after: u32,
+    /// Whether to check other peers' content when asked.
+    /// Default: true.
+    pub serve: bool,
+}
+
+impl Default for AvailabilityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            check_interval_secs: 3_600,
+            sample_size: 3,
+            checkers: Vec::new(),
+            timeout_ms: 30_000,
+            alert_after: 2,
+            serve: true,
+        }
+    }
+}
+
+impl AvailabilityConfig {
+    /// Set whether our content is checked periodically.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Set how often content is checked, in seconds (at least 1).
+    pub fn with_check_interval(mut self, secs: u64) -> Self {
+        self.check_interval_secs = secs.max(1);
+        self
+    }
+
+    /// Set how much content is checked per run (at least 1).
+    pub fn with_sample_size(mut self, sample_size: usize) -> Self {
+        self.sample_size = sample_size.max(1);
+        self
+    }
+
+    /// Set the peers preferred as checkers.
+    pub fn with_checkers(mut self, checkers: Vec<PeerId>) -> Self {
+        self.checkers = checkers;
+        self
+    }
+
+    /// Set the per-check timeout in milliseconds.
+    pub fn with_timeout(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    /// Set how many consecutive failures are reported (at least 1).
+    pub fn with_alert_after(mut self, alert_after: u32) -> Self {
+        self.alert_after = alert_after.max(1);
+        self
+    }
+
+    /// Set whether to check other peers' content when asked.
+    pub fn with_serve(mut self, serve: bool) -> Self {
+        self.serve = serve;
+        self
+    }
+}
+
+/// State snapshots and fast sync.
+///
+/// Nodes with `serve` set answer snapshot requests with a signed snapshot
+/// of their announcement index and peer list. New nodes import one with
+/// `fast_sync` instead of waiting for gossip.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SnapshotConfig {
+    /// Whether to answer snapshot requests.
+    /// Default: false.
+    pub serve: bool,
+    /// Most announcements in a snapshot we take or ask for.
+    /// Default: `MAX_SNAPSHOT_ANNOUNCEMENTS`.
+    pub max_announcements: usize,
+    /// Most peer records in a snapshot we take or ask for.
+    /// Default: `MAX_SNAPSHOT_PEERS`.
+    pub max_peers: usize,
+    /// Oldest snapshot imported, in milliseconds.
+    /// Default: 600_000 (10 minutes).
+    pub max_age_ms: u64,
+    /// Peers from an imported snapshot dialed straight away.
+    /// Default: 8.
+    pub dial_peers: usize,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            serve: false,
+            max_announcements: MAX_SNAPSHOT_ANNOUNCEMENTS,
+            max_peers: MAX_SNAPSHOT_PEERS,
+            max_age_ms: 600_000,
+            dial_peers: 8,
+        }
+    }
+}
+
+impl SnapshotConfig {
+    /// Set whether to answer snapshot requests.
+    pub fn with_serve(mut self, serve: bool) -> Self {
+        self.serve = serve;
+        self
+    }
+
+    /// Set the most announcements and peer records in a snapshot (capped
+    /// at the protocol limits).
+    pub fn with_limits(mut self, max_announcements: usize, max_peers: usize) -> Self {
+        self.max_announcements = max_announcements.min(MAX_SNAPSHOT_ANNOUNCEMENTS);
+        self.max_peers = max_peers.min(MAX_SNAPSHOT_PEERS);
+        self
+    }
+
+    /// Set the oldest snapshot imported, in milliseconds.
+    pub fn with_max_age(mut self, max_age_ms: u64) -> Self {
+        self.max_age_ms = max_age_ms;
+        self
+    }
+
+    /// Set how many peers from an imported snapshot are dialed.
+    pub fn with_dial_peers(mut self, dial_peers: usize) -> Self {
+        self.dial_peers = dial_peers;
+        self
+    }
+}
+
+/// Storefront publishing and fetching.
+///
+/// With `enabled`, a running node signs a storefront listing its shared
+/// content every `refresh_interval_secs` and stores it in the DHT under its
+/// peer ID. `featured` content is listed first and marked for consumers to
+/// show prominently.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorefrontConfig {
+    /// Whether our storefront is republished periodically.
+    /// Default: false.
+    pub enabled: bool,
+    /// Display name of our storefront.
+    /// Default: empty.
+    pub name: String,
+    /// Content featured in our storefront, in display order.
+    /// Default: empty.
+    pub featured: Vec<Hash>,
+    /// How often our storefront is regenerated and republished, in seconds.
+    /// Default: 3_600.
+    pub refresh_interval_secs: u64,
+    /// Oldest storefront accepted when fetching, in milliseconds.
+    /// Default: 172_800_000 (2 days).
+    pub max_age_ms: u64,
+}
+
+impl Default for StorefrontConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            name: String::new(),
+            featured: Vec::new(),
+            refresh_interval_secs: 3_600,
+            max_age_ms: 172_800_000,
+        }
+    }
+}
+
+impl StorefrontConfig {
+    /// Set whether our storefront is republished periodically.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Set the display name of our storefront.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Set the featured content, in display order.
+    pub fn with_featured(mut self, featured: Vec<Hash>) -> Self {
+        self.featured = featured;
+        self
+    }
+
+    /// Set how often our storefront is republished, in seconds (at least 1).
+    pub fn with_refresh_interval(mut self, secs: u64) -> Self {
+        self.refresh_interval_secs = secs.max(1);
+        self
+    }
+
+    /// Set the oldest storefront accepted when fetching, in milliseconds.
+    pub fn with_max_age(mut self, max_age_ms: u64) -> Self {
+        self.max_age_ms = max_age_ms;
+        self
+    }
+}
+
+/// Holding sync bundles for other identities' devices.
+///
+/// Nodes with `serve` set keep the newest encrypted sync bundle each
+/// owner pushes, and hand it back to the owner's other devices.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SyncConfig {
+    /// Whether to hold sync bundles pushed by other peers.
+    /// Default: false.
+    pub serve: bool,
+    /// Largest bundle held, in bytes.
+    /// Default: 1 MiB.
+    pub max_bundle_bytes: usize,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            serve: false,
+            max_bundle_bytes: 1024 * 1024,
+        }
+    }
+}
+
+impl SyncConfig {
+    /// Set whether to hold sync bundles pushed by other peers.
+    pub fn with_serve(mut self, serve: bool) -> Self {
+        self.serve = serve;
+        self
+    }
+
+    /// Set the largest bundle held, in bytes.
+    pub fn with_max_bundle_bytes(mut self, max_bundle_bytes: usize) -> Self {
+        self.max_bundle_bytes = max_bundle_bytes;
+        self
+    }
+}
+
+/// Re-downloads of content requesters bought from us.
+///
+/// A requester whose cached copy was evicted can present the receipt of
+/// its paid query to be served the same content version again for free,
+/// for `window_ms` after the purchase. Only whole-content queries entitle
+/// to re-downloads.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EntitlementConfig {
+    /// Whether we serve re-downloads.
+    /// Default: true.
+    pub enabled: bool,
+    /// How long after a purchase it can be re-downloaded, in milliseconds.
+    /// Default: 30 days.
+    pub window_ms: u64,
+}
+
+impl Default for EntitlementConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_ms: 30 * 24 * 60 * 60 * 1000,
+        }
+    }
+}
+
+impl EntitlementConfig {
+    /// Set whether we serve re-downloads.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Set how long after a purchase it can be re-downloaded, in
+    /// milliseconds.
+    pub fn with_window_ms(mut self, window_ms: u64) -> Self {
+        self.window_ms = window_ms;
+        self
+    }
+}
+
+/// Watching the disputes on our channels.
+///
+/// Every `check_interval_secs` each open channel and tracked dispute is
+/// looked up on-chain. New disputes and those whose deadline is within
+/// `warn_before_ms` are reported, and with `auto_counter` a dispute
+/// submitted with an older state than ours is countered with our latest
+/// one before the deadline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisputeConfig {
+    /// Whether disputes are watched.
+    /// Default: true.
+    pub enabled: bool,
+    /// Whether stale states are countered automatically.
+    /// Default: true.
+    pub auto_counter: bool,
+    /// How long before a deadline it is warned about, in milliseconds.
+    /// Default: 6 hours.
+    pub warn_before_ms: u64,
+    /// How often disputes are checked, in seconds.
+    /// Default: 600.
+    pub check_interval_secs: u64,
+}
+
+impl Default for DisputeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            auto_counter: true,
+            warn_before_ms: 6 * 60 * 60 * 1000,
+            check_interval_secs: 600,
+        }
+    }
+}
+
+impl DisputeConfig {
+    /// Set whether disputes are watched.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Set whether stale states are countered automatically.
+    pub fn with_auto_counter(mut self, auto_counter: bool) -> Self {
+        self.auto_counter = auto_counter;
+        self
+    }
+
+    /// Set how long before a deadline it is warned about, in milliseconds.
+    pub fn with_warn_before_ms(mut self, warn_before_ms: u64) -> Self {
+        self.warn_before_ms = warn_before_ms;
+        self
+    }
+
+    /// Set how often disputes are checked, in seconds (at least 1).
+    pub fn with_check_interval(mut self, secs: u64) -> Self {
+        self.check_interval_secs = secs.max(1);
+        self
+    }
+}
+
+/// Content popularity tracking and cache prewarming.
+///
+/// Previews and queries served, searches matched and queries made locally
+/// are counted per hash into a score that halves every `half_life_ms`.
+/// Each prewarm run keeps the manifests of the most popular content in
+/// memory and refreshes popular cached content so it isn't evicted; with
+/// `fetch` set it also fetches popular free content we don't hold.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PopularityConfig {
+    /// Whether to count requests.
+    /// Default: true.
+    pub enabled: bool,
+    /// Time for a score to halve, in milliseconds.
+    /// Default: 86_400_000 (1 day).
+    pub half_life_ms: u64,
+    /// Minimum score for content to count as popular.
+    /// Default: 2.0.
+    pub min_score: f64,
+    /// Manifests of the most popular content held in memory.
+    /// Default: 64.
+    pub hot_manifests: usize,
+    /// Whether to fetch popular free content we don't hold.
+    /// Default: false.
+    pub fetch: bool,
+    /// Most content fetched per prewarm run.
+    /// Default: 8.
+    pub max_fetch: usize,
+    /// Seconds between prewarm runs.
+    /// Default: 300.
+    pub prewarm_interval_secs: u64,
+}
+
+impl Default for PopularityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            half_life_ms: 86_400_000,
+            min_score: 2.0,
+            hot_manifests: 64,
+            fetch: false,
+            max_fetch: 8,
+            prewarm_interval_secs: 300,
+        }
+    }
+}
+
+impl PopularityConfig {
+    /// Enable or disable request counting.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Set the score half-life in milliseconds.
+    pub fn with_half_life(mut self, half_life_ms: u64) -> Self {
+        self.half_life_ms = half_life_ms;
+        self
+    }
+
+    /// Set the minimum score for content to count as popular.
+    pub fn with_min_score(mut self, min_score: f64) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    /// Set how many popular manifests are held in memory.
+    pub fn with_hot_manifests(mut self, count: usize) -> Self {
+        self.hot_manifests = count;
+        self
+    }
+
+    /// Fetch up to `max_fetch` popular free items per run.
+    pub fn with_fetch(mut self, max_fetch: usize) -> Self {
+        self.fetch = true;
+        self.max_fetch = max_fetch;
+        self
+    }
+
+    /// Set the prewarm interval in seconds (at least 1).
+    pub fn with_prewarm_interval(mut self, secs: u64) -> Self {
+        self.prewarm_interval_secs = secs.max(1);
+        self
+    }
+}
+
+/// Economic and content events that webhooks can be registered for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// A paid query we served was settled.
+    PaymentReceived,
+    /// A query for our content was served (paid or free).
+    ContentQueried,
+    /// A settlement batch was settled.
+    SettlementConfirmed,
+    /// We opened a dispute on a payment channel.
+    ChannelDisputed,
+    /// A counterparty opened a dispute on one of our channels.
+    DisputeOpened,
+    /// The deadline to respond to a dispute is near.
+    DisputeDeadline,
+    /// We countered a dispute with our latest state.
+    DisputeCountered,
+    /// Our content failed repeated availability checks.
+    ContentUnreachable,
+}
+
+impl WebhookEvent {
+    /// Get the name sent as the event type.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::PaymentReceived => "payment_received",
+            WebhookEvent::ContentQueried => "content_queried",
+            WebhookEvent::SettlementConfirmed => "settlement_confirmed",
+            WebhookEvent::ChannelDisputed => "channel_disputed",
+            WebhookEvent::DisputeOpened => "dispute_opened",
+            WebhookEvent::DisputeDeadline => "dispute_deadline",
+            WebhookEvent::DisputeCountered => "dispute_countered",
+            WebhookEvent::ContentUnreachable => "content_unreachable",
+        }
+    }
+}
+
+impl std::fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A URL notified of events.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookEndpoint {
+    /// URL the events are posted to.
+    pub url: String,
+    /// Events sent to this URL (empty = all events).
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+    /// Shared secret the body is signed with (HMAC-SHA256).
+    pub secret: String,
+}
+
+impl WebhookEndpoint {
+    /// Create an endpoint receiving all events.
+    pub fn new(url: impl Into<String>, secret: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            events: Vec::new(),
+            secret: secret.into(),
+        }
+    }
+
+    /// Only send the given events to this endpoint.
+    pub fn with_events(mut self, events: Vec<WebhookEvent>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Whether this endpoint wants the event.
+    pub fn wants(&self, event: WebhookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+/// Webhooks notifying external systems of economic and content events.
+///
+/// Each event is queued once per registered endpoint and posted by
+/// `deliver_webhooks`, which the node runs every `delivery_interval_secs`.
+/// Failed posts are retried with exponential backoff, starting at
+/// `initial_backoff_secs` and capped at `max_backoff_secs`, until
+/// `max_attempts` have been made.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// Registered endpoints.
+    /// Default: none (webhooks off).
+    pub endpoints: Vec<WebhookEndpoint>,
+    /// Attempts made before a delivery is given up on.
+    /// Default: 8.
+    pub max_attempts: u32,
+    /// Delay before the first retry, in seconds.
+    /// Default: 10.
+    pub initial_backoff_secs: u64,
+    /// Longest delay between retries, in seconds.
+    /// Default: 3600 (1 hour).
+    pub max_backoff_secs: u64,
+    /// How long to wait for an endpoint to answer, in milliseconds.
+    /// Default: 10_000.
+    pub timeout_ms: u64,
+    /// Most deliveries attempted per run.
+    /// Default: 64.
+    pub batch_size: u32,
+    /// Seconds between delivery runs.
+    /// Default: 5.
+    pub delivery_interval_secs: u64,
+    /// How long finished deliveries are kept, in seconds.
+    /// Default: 604_800 (7 days).
+    pub history_ttl_secs: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: Vec::new(),
+            max_attempts: 8,
+            initial_backoff_secs: 10,
+            max_backoff_secs: 3600,
+            timeout_ms: 10_000,
+            batch_size: 64,
+            delivery_interval_secs: 5,
+            history_ttl_secs: 7 * 24 * 60 * 60,
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// Register an endpoint.
+    pub fn with_endpoint(mut self, endpoint: WebhookEndpoint) -> Self {
+        self.endpoints.push(endpoint);
+        self
+    }
+
+    /// Set the attempts made before a delivery is given up on (at least 1).
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the first and longest retry delays in seconds.
+    pub fn with_backoff(mut self, initial_secs: u64, max_secs: u64) -> Self {
+        self.initial_backoff_secs = initial_secs;
+        self.max_backoff_secs = max_secs;
+        self
+    }
+
+    /// Set the endpoint timeout in milliseconds.
+    pub fn with_timeout(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    /// Set the delivery interval in seconds (at least 1).
+    pub fn with_delivery_interval(mut self, secs: u64) -> Self {
+        self.delivery_interval_secs = secs.max(1);
+        self
+    }
+
+    /// Whether any endpoint is registered.
+    pub fn is_enabled(&self) -> bool {
+        !self.endpoints.is_empty()
+    }
+
+    /// Delay before retrying after `attempts` failed attempts, in
+    /// milliseconds, or `None` once the attempts are used up.
+    pub fn retry_delay_ms(&self, attempts: u32) -> Option<u64> {
+        if attempts >= self.max_attempts {
+            return None;
+        }
+        let factor = 1u64
+            .checked_shl(attempts.saturating_sub(1))
+            .unwrap_or(u64::MAX);
+        let secs = self
+            .initial_backoff_secs
+            .saturating_mul(factor)
+            .min(self.max_backoff_secs);
+        Some(secs.saturating_mul(1000))
+    }
+}
+
+/// Periodic digests of earnings and activity.
+///
+/// A running node checks every `check_interval_secs` whether a period has
+/// ended since the last digest and, if so, sends a digest of it through
+/// every registered notifier. Periods are UTC days and weeks starting on
+/// Monday.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotificationConfig {
+    /// Send a digest for each day.
+    /// Default: false.
+    pub daily: bool,
+    /// Send a digest for each week.
+    /// Default: false.
+    pub weekly: bool,
+    /// Most earning content listed in a digest.
+    /// Default: 5.
+    pub top_content: usize,
+    /// Seconds between checks for a period that ended.
+    /// Default: 300 (5 minutes).
+    pub check_interval_secs: u64,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            daily: false,
+            weekly: false,
+            top_content: 5,
+            check_interval_secs: 300,
+        }
+    }
+}
+
+impl NotificationConfig {
+    /// Enable or disable digests for a period.
+    pub fn with_period(mut self, period: DigestPeriod, enabled: bool) -> Self {
+        match period {
+            DigestPeriod::Daily => self.daily = enabled,
+            DigestPeriod::Weekly => self.weekly = enabled,
+        }
+        self
+    }
+
+    /// Set how much top content a digest lists.
+    pub fn with_top_content(mut self, top_content: usize) -> Self {
+        self.top_content = top_content;
+        self
+    }
+
+    /// Set the check interval in seconds (at least 1).
+    pub fn with_check_interval(mut self, secs: u64) -> Self {
+        self.check_interval_secs = secs.max(1);
+        self
+    }
+
+    /// Periods digests are sent for.
+    pub fn periods(&self) -> Vec<DigestPeriod> {
+        [
+            (DigestPeriod::Daily, self.daily),
+            (DigestPeriod::Weekly, self.weekly),
+        ]
+        .into_iter()
+        .filter_map(|(period, enabled)| enabled.then_some(period))
+        .collect()
+    }
+
+    /// Whether digests are sent for any period.
+    pub fn is_enabled(&self) -> bool {
+        self.daily || self.weekly
+    }
+}
+
+/// Standing orders: buying announced content automatically.
+///
+/// A running node evaluates its standing orders every `interval_secs`
+/// against the announcements received since each order was created, and
+/// buys up to `max_purchases_per_run` matching items. Bought content is
+/// written to `inbox_dir`, if set, as a file named by its hash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StandingOrderConfig {
+    /// Whether to run standing orders.
+    /// Default: false.
+    pub enabled: bool,
+    /// Directory bought content is written to (`None` = cache only).
+    /// Default: None.
+    pub inbox_dir: Option<PathBuf>,
+    /// Most content bought per run.
+    /// Default: 8.
+    pub max_purchases_per_run: usize,
+    /// Seconds between runs.
+    /// Default: 60.
+    pub interval_secs: u64,
+}
+
+impl Default for StandingOrderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            inbox_dir: None,
+            max_purchases_per_run: 8,
+            interval_secs: 60,
+        }
+    }
+}
+
+impl StandingOrderConfig {
+    /// Enable or disable standing orders.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Write bought content to a directory.
+    pub fn with_inbox_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.inbox_dir = Some(dir.into());
+        self
+    }
+
+    /// Set the most content bought per run.
+    pub fn with_max_purchases_per_run(mut self, max: usize) -> Self {
+        self.max_purchases_per_run = max;
+        self
+    }
+
+    /// Set the interval between runs in seconds (at least 1).
+    pub fn with_interval(mut self, secs: u64) -> Self {
+        self.interval_secs = secs.max(1);
+        self
+    }
+}
+
+/// How the current version of a lineage is chosen when the owner has
+/// published more than one version with the same number.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForkPolicy {
+    /// The fork with the latest timestamp wins.
+    #[default]
+    LatestTimestamp,
+    /// The version named by the owner's signed canonical pointer wins,
+    /// falling back to the latest timestamp without a pointer.
+    OwnerCanonical,
+}
+
+/// Configuration for closing all channels at once (e.g. on shutdown).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CloseBatchConfig {
+    /// Maximum number of peers contacted at the same time.
+    pub max_concurrent: usize,
+    /// How long to wait for each peer's close acknowledgement, in milliseconds.
+    pub peer_timeout_ms: u64,
+}
+
+impl Default for CloseBatchConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 8,
+            peer_timeout_ms: 3_000,
+        }
+    }
+}
+
+impl CloseBatchConfig {
+    /// Set the maximum number of concurrent closes (at least 1).
+    pub fn with_max_concurrent(mut self, max: usize) -> Self {
+        self.max_concurrent = max.max(1);
+        self
+    }
+
+    /// Set the per-peer acknowledgement timeout in milliseconds.
+    pub fn with_peer_timeout(mut self, timeout_ms: u64) -> Self {
+        self.peer_timeout_ms = timeout_ms;
+        self
+    }
+}
+
+/// Per-content access analytics configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnalyticsConfig {
+    /// Whether previews and queries of local content are recorded.
+    pub enabled: bool,
+    /// Store a hash of each requester's peer ID instead of the ID itself.
+    ///
+    /// Unique consumer counts still work, but the log can't be traced back
+    /// to individual peers.
+    pub hash_requesters: bool,
+}
+
+impl Default for AnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            hash_requesters: false,
+        }
+    }
+}
+
+impl AnalyticsConfig {
+    /// Enable or disable access recording.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Enable or disable requester hashing.
+    pub fn with_hash_requesters(mut self, hash: bool) -> Self {
+        self.hash_requesters = hash;
+        self
+    }
+}
+
+/// Configuration for usage reports (read receipts) between consumers and
+/// publishers.
+///
+/// Both ends opt in separately: a consumer only sends reports with `send`
+/// enabled, and a publisher only records them with `accept` enabled.
+/// Reports are stored without the sender's identity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UsageReportConfig {
+    /// Whether we send usage reports for content we queried.
+    /// Default: false.
+    pub send: bool,
+    /// Whether we record usage reports for our own content.
+    /// Default: false.
+    pub accept: bool,
+    /// Maximum reports accepted from one peer per hour.
+    pub max_per_peer_per_hour: u32,
+}
+
+impl Default for UsageReportConfig {
+    fn default() -> Self {
+        Self {
+            send: false,
+            accept: false,
+            max_per_peer_per_hour: 30,
+        }
+    }
+}
+
+impl UsageReportConfig {
+    /// Enable or disable sending usage reports.
+    pub fn with_send(mut self, send: bool) -> Self {
+        self.send = send;
+        self
+    }
+
+    /// Enable or disable accepting usage reports.
+    pub fn with_accept(mut self, accept: bool) -> Self {
+        self.accept = accept;
+        self
+    }
+
+    /// Set the maximum reports accepted from one peer per hour.
+    pub fn with_max_per_peer_per_hour(mut self, max: u32) -> Self {
+        self.max_per_peer_per_hour = max;
+        self
+    }
+}
+
+/// Proof-of-possession challenges for queries this node serves.
+///
+/// Paid queries for content priced at `min_price` or more are answered
+/// with a single-use challenge first, which the requester must sign with
+/// its identity key and retry within `ttl_ms`. This stops a third party
+/// replaying a captured query request. Disabled by default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QueryChallengeConfig {
+    /// Lowest content price that is challenged (`None` = never challenge).
+    pub min_price: Option<Amount>,
+    /// How long a challenge can be answered, in milliseconds.
+    pub ttl_ms: u64,
+}
+
+impl Default for QueryChallengeConfig {
+    fn default() -> Self {
+        Self {
+            min_price: None,
+            ttl_ms: 60_000,
+        }
+    }
+}
+
+impl QueryChallengeConfig {
+    /// Challenge paid queries for content priced at `min_price` or more.
+    pub fn with_min_price(mut self, min_price: Amount) -> Self {
+        self.min_price = Some(min_price);
+        self
+    }
+
+    /// Set how long a challenge can be answered, in milliseconds.
+    pub fn with_ttl_ms(mut self, ttl_ms: u64) -> Self {
+        self.ttl_ms = ttl_ms;
+        self
+    }
+
+    /// Whether a paid query for content at `price` must be challenged.
+    pub fn applies_to(&self, price: Amount) -> bool {
+        price > 0 && self.min_price.is_some_and(|min| price >= min)
+    }
+}
+
+/// Limit on the queries this node serves at once.
+///
+/// A query arriving while `max_concurrent` are being served is refused as
+/// busy, telling the requester to retry after `retry_after_ms` and where
+/// else the content may be found.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QueryLimitConfig {
+    /// Most queries served at once. Default: 64.
+    pub max_concurrent: usize,
+    /// Delay suggested to refused requesters, in milliseconds.
+    /// Default: 1000.
+    pub retry_after_ms: u64,
+}
+
+impl Default for QueryLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 64,
+            retry_after_ms: 1_000,
+        }
+    }
+}
+
+impl QueryLimitConfig {
+    /// Set the most queries served at once.
+    pub fn with_max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = max_concurrent;
+        self
+    }
+
+    /// Set the delay suggested to refused requesters, in milliseconds.
+    pub fn with_retry_after_ms(mut self, retry_after_ms: u64) -> Self {
+        self.retry_after_ms = retry_after_ms;
+        self
+    }
+}
+
+/// How our queries react to temporary errors from providers.
+///
+/// A provider answering with a retry delay is asked again after waiting,
+/// up to `max_retries` times, unless the delay is longer than
+/// `max_wait_ms`. Alternative providers it names are tried when it can't
+/// serve the content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QueryRetryConfig {
+    /// Retries of the same provider. Default: 2.
+    pub max_retries: u32,
+    /// Longest retry delay we wait out, in milliseconds. Default: 5000.
+    pub max_wait_ms: u64,
+    /// Whether to try alternative providers. Default: true.
+    pub try_alternatives: bool,
+}
+
+impl Default for QueryRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            max_wait_ms: 5_000,
+            try_alternatives: true,
+        }
+    }
+}
+
+impl QueryRetryConfig {
+    /// Set the retries of the same provider.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the longest retry delay waited out, in milliseconds.
+    pub fn with_max_wait_ms(mut self, max_wait_ms: u64) -> Self {
+        self.max_wait_ms = max_wait_ms;
+        self
+    }
+
+    /// Set whether alternative providers are tried.
+    pub fn with_try_alternatives(mut self, try_alternatives: bool) -> Self {
+        self.try_alternatives = try_alternatives;
+        self
+    }
+}
+
+/// Publisher bonds required of the peers we fetch content from.
+///
+/// Before paying for content, the publisher's bond claimed in the manifest
+/// is checked against `requirements` for its visibility and, with a
+/// settlement layer, against the chain. Looked-up bonds are cached for
+/// `cache_ttl_ms`. With `slash_on_fraud`, a publisher that serves content
+/// not matching its hash has the bond it claimed slashed. No bonds are
+/// required by default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BondConfig {
+    /// Minimum publisher bond for each visibility.
+    pub requirements: BondRequirements,
+    /// Whether to slash the bond of a publisher caught serving bad content.
+    /// Default: false.
+    pub slash_on_fraud: bool,
+    /// How long a publisher's on-chain bond is cached, in milliseconds.
+    pub cache_ttl_ms: u64,
+}
+
+impl Default for BondConfig {
+    fn default() -> Self {
+        Self {
+            requirements: BondRequirements::default(),
+            slash_on_fraud: false,
+            cache_ttl_ms: 300_000,
+        }
+    }
+}
+
+impl BondConfig {
+    /// Set the minimum publisher bonds.
+    pub fn with_requirements(mut self, requirements: BondRequirements) -> Self {
+        self.requirements = requirements;
+        self
+    }
+
+    /// Set whether to slash the bonds of publishers serving bad content.
+    pub fn with_slash_on_fraud(mut self, slash_on_fraud: bool) -> Self {
+        self.slash_on_fraud = slash_on_fraud;
+        self
+    }
+
+    /// Set how long looked-up bonds are cached, in milliseconds.
+    pub fn with_cache_ttl_ms(mut self, cache_ttl_ms: u64) -> Self {
+        self.cache_ttl_ms = cache_ttl_ms;
+        self
+    }
+}
+
+/// How fraud proofs against providers are acted on.
+///
+/// A provider proven to have served the wrong content loses
+/// `reputation_penalty` reputation, once per proof, whether we caught it
+/// or a peer broadcast the proof. Proofs we make are always broadcast, and
+/// recorded on-chain with `submit_on_chain`. Bonds are slashed on proofs
+/// when [`BondConfig::slash_on_fraud`] is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FraudProofConfig {
+    /// Whether we act on fraud proofs broadcast by other peers.
+    /// Default: true.
+    pub accept_proofs: bool,
+    /// Reputation a provider loses for each proof against it.
+    /// Default: 10.
+    pub reputation_penalty: i64,
+    /// Whether proofs we make are recorded on-chain.
+    /// Default: false.
+    pub submit_on_chain: bool,
+}
+
+impl Default for FraudProofConfig {
+    fn default() -> Self {
+        Self {
+            accept_proofs: true,
+            reputation_penalty: 10,
+            submit_on_chain: false,
+        }
+    }
+}
+
+impl FraudProofConfig {
+    /// Enable or disable acting on proofs from other peers.
+    pub fn with_accept_proofs(mut self, accept: bool) -> Self {
+        self.accept_proofs = accept;
+        self
+    }
+
+    /// Set the reputation a provider loses for each proof.
+    pub fn with_reputation_penalty(mut self, penalty: i64) -> Self {
+        self.reputation_penalty = penalty;
+        self
+    }
+
+    /// Enable or disable recording our proofs on-chain.
+    pub fn with_submit_on_chain(mut self, submit: bool) -> Self {
+        self.submit_on_chain = submit;
+        self
+    }
+}
+
+/// Moderation policy for content reports from other peers.
+///
+/// Accepted reports queue the content for review. With an
+/// `auto_hide_threshold`, content is also hidden from search results once
+/// that many distinct peers with at least `min_reporter_reputation` have
+/// reported it. Our own content is never hidden automatically.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModerationConfig {
+    /// Whether we record reports from other peers.
+    /// Default: true.
+    pub accept_reports: bool,
+    /// Reports from reputable peers that hide content automatically
+    /// (`None` = only hide content manually).
+    /// Default: None.
+    pub auto_hide_threshold: Option<u32>,
+    /// Minimum reputation for a reporter to count towards the auto-hide
+    /// threshold (unknown peers count as 0).
+    /// Default: 1.
+    pub min_reporter_reputation: i64,
+}
+
+impl Default for ModerationConfig {
+    fn default() -> Self {
+        Self {
+            accept_reports: true,
+            auto_hide_threshold: None,
+            min_reporter_reputation: 1,
+        }
+    }
+}
+
+impl ModerationConfig {
+    /// Enable or disable accepting reports from other peers.
+    pub fn with_accept_reports(mut self, accept: bool) -> Self {
+        self.accept_reports = accept;
+        self
+    }
+
+    /// Hide content automatically after `threshold` reputable reports.
+    pub fn with_auto_hide_threshold(mut self, threshold: u32) -> Self {
+        self.auto_hide_threshold = Some(threshold);
+        self
+    }
+
+    /// Set the minimum reputation for a reporter to count.
+    pub fn with_min_reporter_reputation(mut self, min: i64) -> Self {
+        self.min_reporter_reputation = min;
+        self
+    }
+}
+
+/// Federated search configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchConfig {
+    /// Maximum number of peers a search fans out to, most reputable first.
+    pub max_peers: usize,
+    /// How long to wait for each peer's results, in milliseconds.
+    pub peer_timeout_ms: u64,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            max_peers: 5,
+            peer_timeout_ms: 2_000,
+        }
+    }
+}
+
+impl SearchConfig {
+    /// Set the maximum number of peers searched.
+    pub fn with_max_peers(mut self, max: usize) -> Self {
+        self.max_peers = max;
+        self
+    }
+
+    /// Set the per-peer search deadline in milliseconds.
+    pub fn with_peer_timeout(mut self, timeout_ms: u64) -> Self {
+        self.peer_timeout_ms = timeout_ms;
+        self
+    }
+}
+
+/// Filters for announcements received over GossipSub.
+///
+/// Announcements that fail a filter are dropped before they reach the
+/// announcement cache. See [`crate::announce_filter`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnnouncementFilterConfig {
+    /// Minimum reputation of the publisher (unknown publishers count as 0).
+    /// `None` accepts any publisher.
+    pub min_reputation: Option<i64>,
+    /// Lowest acceptable price.
+    pub min_price: Amount,
+    /// Highest acceptable price.
+    pub max_price: Amount,
+    /// Titles containing any of these (case-insensitive) are dropped.
+    pub title_blocklist: Vec<String>,
+    /// Titles matching any of these regular expressions are dropped.
+    /// Invalid patterns are ignored with a warning.
+    pub title_patterns: Vec<String>,
+    /// Maximum announcements accepted from one peer per minute.
+    /// `None` disables rate limiting.
+    pub max_per_peer_per_minute: Option<u32>,
+    /// Replace a publisher's cached announcement when it announces new
+    /// content under the same title, instead of keeping both.
+    pub collapse_duplicate_titles: bool,
+}
+
+impl Default for AnnouncementFilterConfig {
+    fn default() -> Self {
+        Self {
+            min_reputation: None,
+            min_price: 0,
+            max_price: nodalync_types::MAX_PRICE,
+            title_blocklist: Vec::new(),
+            title_patterns: Vec::new(),
+            max_per_peer_per_minute: Some(60),
+            collapse_duplicate_titles: true,
+        }
+    }
+}
+
+impl AnnouncementFilterConfig {
+    /// Require a minimum publisher reputation.
+    pub fn with_min_reputation(mut self, reputation: i64) -> Self {
+        self.min_reputation = Some(reputation);
+        self
+    }
+
+    /// Set the acceptable price range.
+    pub fn with_price_range(mut self, min: Amount, max: Amount) -> Self {
+        self.min_price = min;
+        self.max_price = max;
+        self
+    }
+
+    /// Drop titles containing any of these words or phrases.
+    pub fn with_title_blocklist(mut self, blocklist: Vec<String>) -> Self {
+        self.title_blocklist = blocklist;
+        self
+    }
+
+    /// Drop titles matching any of these regular expressions.
+    pub fn with_title_patterns(mut self, patterns: Vec<String>) -> Self {
+        self.title_patterns = patterns;
+        self
+    }
+
+    /// Set the per-peer rate limit (`None` disables it).
+    pub fn with_max_per_peer_per_minute(mut self, max: Option<u32>) -> Self {
+        self.max_per_peer_per_minute = max;
+        self
+    }
+
+    /// Enable or disable duplicate-title collapsing.
+    pub fn with_collapse_duplicate_titles(mut self, collapse: bool) -> Self {
+        self.collapse_duplicate_titles = collapse;
+        self
+    }
+}
+
+/// Batched storage of announcements received over GossipSub.
+///
+/// Used once ingestion is started. Accepted announcements are stored in one
+/// transaction per `batch_size`, or per `flush_interval_ms` when fewer
+/// arrive. At most `max_pending` wait to be stored; beyond that they are
+/// dropped and the network sheds announcements until half have been
+/// stored. See [`crate::ingest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnnouncementIngestConfig {
+    /// Most announcements stored per transaction.
+    /// Default: 256.
+    pub batch_size: usize,
+    /// Longest an announcement waits for its batch to fill, in milliseconds.
+    /// Default: 250.
+    pub flush_interval_ms: u64,
+    /// Most announcements waiting to be stored.
+    /// Default: 4096.
+    pub max_pending: usize,
+}
+
+impl Default for AnnouncementIngestConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 256,
+            flush_interval_ms: 250,
+            max_pending: 4096,
+        }
+    }
+}
+
+impl AnnouncementIngestConfig {
+    /// Set the batch size (at least 1).
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Set the flush interval in milliseconds.
+    pub fn with_flush_interval(mut self, interval_ms: u64) -> Self {
+        self.flush_interval_ms = interval_ms;
+        self
+    }
+
+    /// Set the most announcements waiting to be stored (at least 1).
+    pub fn with_max_pending(mut self, max_pending: usize) -> Self {
+        self.max_pending = max_pending.max(1);
+        self
+    }
+}
+
+/// Relative weights of the trust checks in the trust score.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrustWeights {
+    /// Weight of the publisher reputation check.
+    pub reputation: u32,
+    /// Weight of the attestation check.
+    pub attestation: u32,
+    /// Weight of the provenance depth check.
+    pub depth: u32,
+    /// Weight of the license check.
+    pub license: u32,
+    /// Weight of the price check.
+    pub price: u32,
+}
+
+impl Default for TrustWeights {
+    fn default() -> Self {
+        Self {
+            reputation: 1,
+            attestation: 1,
+            depth: 1,
+            license: 1,
+            price: 1,
+        }
+    }
+}
+
+impl TrustWeights {
+    /// Get the weight of a check.
+    pub fn of(&self, check: TrustCheck) -> u32 {
+        match check {
+            TrustCheck::Reputation => self.reputation,
+            TrustCheck::Attestation => self.attestation,
+            TrustCheck::Depth => self.depth,
+            TrustCheck::License => self.license,
+            TrustCheck::Price => self.price,
+        }
+    }
+}
+
+/// Rules for accepting content received from other peers.
+///
+/// Each configured check passes or fails; the trust score is the weighted
+/// share of passing checks, from 0 to 100. Content is rejected when a check
+/// in `reject_on` fails or the score is below `reject_below`, flagged when
+/// the score is below `flag_below`, and accepted otherwise. With no checks
+/// configured (the default) everything is accepted. See [`crate::trust`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrustPolicy {
+    /// Minimum reputation of the publisher (unknown publishers count as 0).
+    pub min_reputation: Option<i64>,
+    /// Require the manifest to claim an on-chain attestation.
+    pub require_attestation: bool,
+    /// Maximum provenance depth.
+    pub max_depth: Option<u32>,
+    /// Licenses (`license` in the structured metadata) that are accepted,
+    /// case-insensitive. Empty accepts any license.
+    pub allowed_licenses: Vec<String>,
+    /// Require content to declare a license.
+    pub require_license: bool,
+    /// Highest acceptable price.
+    pub max_price: Option<Amount>,
+    /// Weights of the checks in the score.
+    pub weights: TrustWeights,
+    /// Flag content scoring below this (0-100).
+    /// Default: 100, so any failed check flags.
+    pub flag_below: u8,
+    /// Reject content scoring below this (0-100).
+    /// Default: 0, so the score alone never rejects.
+    pub reject_below: u8,
+    /// Checks whose failure always rejects.
+    pub reject_on: Vec<TrustCheck>,
+}
+
+impl Default for TrustPolicy {
+    fn default() -> Self {
+        Self {
+            min_reputation: None,
+            require_attestation: false,
+            max_depth: None,
+            allowed_licenses: Vec::new(),
+            require_license: false,
+            max_price: None,
+            weights: TrustWeights::default(),
+            flag_below: 100,
+            reject_below: 0,
+            reject_on: Vec::new(),
+        }
+    }
+}
+
+impl TrustPolicy {
+    /// Require a minimum publisher reputation.
+    pub fn with_min_reputation(mut self, reputation: i64) -> Self {
+        self.min_reputation = Some(reputation);
+        self
+    }
+
+    /// Require an attestation claim.
+    pub fn with_require_attestation(mut self, require: bool) -> Self {
+        self.require_attestation = require;
+        self
+    }
+
+    /// Set the maximum provenance depth.
+    pub fn with_max_depth(mut self, depth: u32) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Accept only these licenses.
+    pub fn with_allowed_licenses(mut self, licenses: Vec<String>) -> Self {
+        self.allowed_licenses = licenses;
+        self
+    }
+
+    /// Require content to declare a license.
+    pub fn with_require_license(mut self, require: bool) -> Self {
+        self.require_license = require;
+        self
+    }
+
+    /// Set the highest acceptable price.
+    pub fn with_max_price(mut self, price: Amount) -> Self {
+        self.max_price = Some(price);
+        self
+    }
+
+    /// Set the check weights.
+    pub fn with_weights(mut self, weights: TrustWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    /// Set the flag and reject score thresholds (each capped at 100).
+    pub fn with_thresholds(mut self, flag_below: u8, reject_below: u8) -> Self {
+        self.flag_below = flag_below.min(100);
+        self.reject_below = reject_below.min(100);
+        self
+    }
+
+    /// Reject content when any of these checks fails.
+    pub fn with_reject_on(mut self, checks: Vec<TrustCheck>) -> Self {
+        self.reject_on = checks;
+        self
+    }
+}
+
+/// Content recommendation configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecommendationConfig {
+    /// Number of recent queries that feed the interest profile.
+    pub history_limit: u32,
+    /// Minimum score for an announcement to be recommended.
+    pub min_score: f64,
+}
+
+impl Default for RecommendationConfig {
+    fn default() -> Self {
+        Self {
+            history_limit: 50,
+            min_score: 1.0,
+        }
+    }
+}
+
+impl RecommendationConfig {
+    /// Set how many recent queries feed the interest profile.
+    pub fn with_history_limit(mut self, limit: u32) -> Self {
+        self.history_limit = limit;
+        self
+    }
+
+    /// Set the minimum recommendation score.
+    pub fn with_min_score(mut self, score: f64) -> Self {
+        self.min_score = score;
+        self
+    }
+}
+
+/// A channel open that the query path wants to perform on its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoOpenRequest {
+    /// Provider's Nodalync peer ID, if already known.
+    pub peer: Option<PeerId>,
+    /// Deposit the channel would lock.
+    pub deposit: Amount,
+    /// Provider's reputation (0 for peers we have not seen).
+    pub reputation: i64,
+}
+
+/// Callback asked to approve each auto-open that passes the policy limits.
+pub type AutoOpenApprover = Arc<dyn Fn(&AutoOpenRequest) -> bool + Send + Sync>;
+
+/// Limits on channels opened automatically while querying.
+///
+/// Opening a channel locks a deposit, so an agent querying arbitrary
+/// providers could otherwise drain the node's balance.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AutoOpenPolicy {
+    /// Whether the query path may open channels at all.
+    pub enabled: bool,
+    /// Largest deposit for a single auto-opened channel.
+    pub max_deposit_per_peer: Amount,
+    /// No auto-open once this many channels are active.
+    pub max_total_channels: usize,
+    /// Providers below this reputation are refused.
+    pub min_provider_reputation: i64,
+    /// Auto-opens allowed in any 24 hour window.
+    pub max_opens_per_day: u32,
+    /// Asked last; `None` approves everything within the limits.
+    /// Set in code only.
+    #[serde(skip)]
+    pub approver: Option<AutoOpenApprover>,
+}
+
+impl std::fmt::Debug for AutoOpenPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AutoOpenPolicy")
+            .field("enabled", &self.enabled)
+            .field("max_deposit_per_peer", &self.max_deposit_per_peer)
+            .field("max_total_channels", &self.max_total_channels)
+            .field("min_provider_reputation", &self.min_provider_reputation)
+            .field("max_opens_per_day", &self.max_opens_per_day)
+            .field("approver", &self.approver.is_some())
+            .finish()
+    }
+}
+
+impl Default for AutoOpenPolicy {
     fn default() -> Self {
         Self {
+            enabled: true,
             // 100 HBAR in tinybars
-            min_deposit: 100_0000_0000,
-            // 1000 HBAR in tinybars
-            default_deposit: 1000_0000_0000,
-            // 500 HBAR max accept deposit
-            max_accept_deposit: 500_0000_0000,
-            // Disabled by default for security
-            auto_deposit_on_channel_open: false,
-            // 200 HBAR auto-deposit amount
-            auto_deposit_amount: 200_0000_0000,
-            // 100 HBAR minimum balance threshold
-            auto_deposit_min_balance: 100_0000_0000,
-            // 5 minute cooldown
-            auto_deposit_cooldown_secs: 300,
+            max_deposit_per_peer: 100_0000_0000,
+            max_total_channels: 20,
+            // Unknown peers start at 0; only penalized peers are refused
+            min_provider_reputation: 0,
+            max_opens_per_day: 10,
+            approver: None,
         }
     }
 }
 
-impl ChannelConfig {
-    /// Create a new channel configuration with basic settings.
-    pub fn new(min_deposit: Amount, default_deposit: Amount) -> Self {
+impl AutoOpenPolicy {
+    /// Policy that never opens channels from the query path.
+    pub fn disabled() -> Self {
         Self {
-            min_deposit,
-            default_deposit,
+            enabled: false,
             ..Default::default()
         }
     }
 
-    /// Set the maximum deposit to accept when a peer opens a channel.
-    pub fn with_max_accept_deposit(mut self, amount: Amount) -> Self {
-        self.max_accept_deposit = amount;
+    /// Set the largest deposit for a single auto-opened channel.
+    pub fn with_max_deposit_per_peer(mut self, amount: Amount) -> Self {
+        self.max_deposit_per_peer = amount;
         self
     }
 
-    /// Enable or disable auto-deposit on channel open.
-    pub fn with_auto_deposit(mut self, enabled: bool) -> Self {
-        self.auto_deposit_on_channel_open = enabled;
+    /// Set the maximum number of active channels.
+    pub fn with_max_total_channels(mut self, max: usize) -> Self {
+        self.max_total_channels = max;
         self
     }
 
-    /// Set the fixed auto-deposit amount.
-    pub fn with_auto_deposit_amount(mut self, amount: Amount) -> Self {
-        self.auto_deposit_amount = amount;
+    /// Set the minimum provider reputation.
+    pub fn with_min_provider_reputation(mut self, reputation: i64) -> Self {
+        self.min_provider_reputation = reputation;
         self
     }
 
-    /// Set the minimum balance threshold for auto-deposit.
-    pub fn with_auto_deposit_min_balance(mut self, amount: Amount) -> Self {
-        self.auto_deposit_min_balance = amount;
+    /// Set the number of auto-opens allowed per day.
+    pub fn with_max_opens_per_day(mut self, max: u32) -> Self {
+        self.max_opens_per_day = max;
         self
     }
 
-    /// Set the cooldown between auto-deposits in seconds.
-    pub fn with_auto_deposit_cooldown(mut self, secs: u64) -> Self {
-        self.auto_deposit_cooldown_secs = secs;
+    /// Set the callback that approves each auto-open.
+    pub fn with_approver(
+        mut self,
+        approver: impl Fn(&AutoOpenRequest) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.approver = Some(Arc::new(approver));
         self
     }
 }
 
 /// Configuration for operations behavior.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct OpsConfig {
     /// Channel configuration.
     pub channel: ChannelConfig,
+    /// Channel rebalancing configuration.
+    pub rebalance: RebalanceConfig,
+    /// Limits on channels opened automatically while querying.
+    pub auto_open: AutoOpenPolicy,
+    /// Batched channel close configuration.
+    pub close_batch: CloseBatchConfig,
+    /// Per-content access analytics configuration.
+    pub analytics: AnalyticsConfig,
+    /// Usage reports sent to and accepted from other peers.
+    pub usage_reports: UsageReportConfig,
+    /// Moderation policy for content reports.
+    pub moderation: ModerationConfig,
+    /// Trust policy for content received from other peers.
+    pub trust: TrustPolicy,
+    /// Proof-of-possession challenges for paid queries we serve.
+    pub query_challenge: QueryChallengeConfig,
+    /// Limit on the queries we serve at once.
+    pub query_limit: QueryLimitConfig,
+    /// Retries of queries refused with a temporary error.
+    pub query_retry: QueryRetryConfig,
+    /// Publisher bonds required of the peers we fetch content from.
+    pub bonds: BondConfig,
+    /// Fraud proofs against providers serving the wrong content.
+    pub fraud_proofs: FraudProofConfig,
+    /// Content recommendation configuration.
+    pub recommendation: RecommendationConfig,
+    /// Federated search configuration.
+    pub search: SearchConfig,
+    /// Filters for incoming announcements.
+    pub announcement_filter: AnnouncementFilterConfig,
+    /// Batched storage of incoming announcements.
+    pub announcement_ingest: AnnouncementIngestConfig,
+    /// Platform fee taken on queries this node serves (`None` = no fee).
+    pub app_fee: Option<AppFee>,
+    /// Daily quota on each requester across all content we serve
+    /// (`None` = unlimited).
+    pub quota: Option<QuotaPolicy>,
+    /// Automatic settlement balance top-ups.
+    pub top_up: TopUpConfig,
+    /// Data retention policy.
+    pub retention: RetentionConfig,
+    /// Clock skew detection and correction.
+    pub clock: ClockSkewConfig,
+    /// Peer latency measurement and QoS-ranked provider selection.
+    pub qos: QosConfig,
+    /// Content availability health checks.
+    pub availability: AvailabilityConfig,
+    /// State snapshots and fast sync.
+    pub snapshot: SnapshotConfig,
+    /// Storefront publishing and fetching.
+    pub storefront: StorefrontConfig,
+    /// Sync bundles held for other peers.
+    pub sync: SyncConfig,
+    /// Re-downloads of content bought from us.
+    pub entitlements: EntitlementConfig,
+    /// Watching the disputes on our channels.
+    pub disputes: DisputeConfig,
+    /// Popularity tracking and cache prewarming.
+    pub popularity: PopularityConfig,
+    /// Webhooks notified of economic and content events.
+    pub webhooks: WebhookConfig,
+    /// Periodic digests of earnings and activity.
+    pub notifications: NotificationConfig,
+    /// Standing orders buying announced content automatically.
+    pub standing_orders: StandingOrderConfig,
+    /// How forked versions of a lineage are resolved.
+    pub fork_policy: ForkPolicy,
     /// Maximum number of preview mentions to include.
     pub max_preview_mentions: usize,
     /// Settlement threshold (amount that triggers batch settlement).
@@ -104,17 +2141,58 @@ pub struct OpsConfig {
     pub settlement_interval_ms: u64,
     /// Settlement timeout in milliseconds (for query handler).
     pub settlement_timeout_ms: u64,
+    /// How long a cached copy of another peer's group stays fresh, in
+    /// milliseconds, before it is refetched from the group owner.
+    pub group_cache_ttl_ms: u64,
+    /// How long the result of checking a manifest's attestation claim
+    /// on-chain is cached, in milliseconds.
+    pub attestation_cache_ttl_ms: u64,
 }
 
 impl Default for OpsConfig {
     fn default() -> Self {
         Self {
             channel: ChannelConfig::default(),
+            rebalance: RebalanceConfig::default(),
+            auto_open: AutoOpenPolicy::default(),
+            close_batch: CloseBatchConfig::default(),
+            analytics: AnalyticsConfig::default(),
+            usage_reports: UsageReportConfig::default(),
+            moderation: ModerationConfig::default(),
+            trust: TrustPolicy::default(),
+            query_challenge: QueryChallengeConfig::default(),
+            query_limit: QueryLimitConfig::default(),
+            query_retry: QueryRetryConfig::default(),
+            bonds: BondConfig::default(),
+            fraud_proofs: FraudProofConfig::default(),
+            recommendation: RecommendationConfig::default(),
+            search: SearchConfig::default(),
+            announcement_filter: AnnouncementFilterConfig::default(),
+            announcement_ingest: AnnouncementIngestConfig::default(),
+            app_fee: None,
+            quota: None,
+            top_up: TopUpConfig::default(),
+            retention: RetentionConfig::default(),
+            clock: ClockSkewConfig::default(),
+            qos: QosConfig::default(),
+            availability: AvailabilityConfig::default(),
+            snapshot: SnapshotConfig::default(),
+            storefront: StorefrontConfig::default(),
+            sync: SyncConfig::default(),
+            entitlements: EntitlementConfig::default(),
+            disputes: DisputeConfig::default(),
+            popularity: PopularityConfig::default(),
+            webhooks: WebhookConfig::default(),
+            notifications: NotificationConfig::default(),
+            standing_orders: StandingOrderConfig::default(),
+            fork_policy: ForkPolicy::default(),
             max_preview_mentions: 5,
             // From constants
             settlement_threshold: nodalync_types::SETTLEMENT_BATCH_THRESHOLD,
             settlement_interval_ms: nodalync_types::SETTLEMENT_BATCH_INTERVAL_MS,
             settlement_timeout_ms: 30_000,
+            group_cache_ttl_ms: nodalync_types::DEFAULT_GROUP_CACHE_TTL_MS,
+            attestation_cache_ttl_ms: nodalync_types::DEFAULT_ATTESTATION_CACHE_TTL_MS,
         }
     }
 }
@@ -126,6 +2204,210 @@ impl OpsConfig {
         self
     }
 
+    /// Set the channel rebalancing configuration.
+    pub fn with_rebalance(mut self, rebalance: RebalanceConfig) -> Self {
+        self.rebalance = rebalance;
+        self
+    }
+
+    /// Set the channel auto-open policy.
+    pub fn with_auto_open(mut self, auto_open: AutoOpenPolicy) -> Self {
+        self.auto_open = auto_open;
+        self
+    }
+
+    /// Set the batched channel close configuration.
+    pub fn with_close_batch(mut self, close_batch: CloseBatchConfig) -> Self {
+        self.close_batch = close_batch;
+        self
+    }
+
+    /// Set the access analytics configuration.
+    pub fn with_analytics(mut self, analytics: AnalyticsConfig) -> Self {
+        self.analytics = analytics;
+        self
+    }
+
+    /// Set the content recommendation configuration.
+    pub fn with_recommendation(mut self, recommendation: RecommendationConfig) -> Self {
+        self.recommendation = recommendation;
+        self
+    }
+
+    /// Set the federated search configuration.
+    pub fn with_search(mut self, search: SearchConfig) -> Self {
+        self.search = search;
+        self
+    }
+
+    /// Set the incoming announcement filters.
+    pub fn with_announcement_filter(mut self, filter: AnnouncementFilterConfig) -> Self {
+        self.announcement_filter = filter;
+        self
+    }
+
+    /// Set the announcement ingestion configuration.
+    pub fn with_announcement_ingest(mut self, ingest: AnnouncementIngestConfig) -> Self {
+        self.announcement_ingest = ingest;
+        self
+    }
+
+    /// Take a platform fee on queries this node serves.
+    ///
+    /// The fee is settled to its recipient along with the protocol
+    /// distribution and reported in payment receipts.
+    pub fn with_app_fee(mut self, app_fee: AppFee) -> Self {
+        self.app_fee = Some(app_fee);
+        self
+    }
+
+    /// Limit each requester's queries or bytes per day across all content
+    /// we serve.
+    ///
+    /// Applies alongside any quota set on the content itself.
+    pub fn with_quota(mut self, quota: QuotaPolicy) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    /// Set the usage report configuration.
+    pub fn with_usage_reports(mut self, usage_reports: UsageReportConfig) -> Self {
+        self.usage_reports = usage_reports;
+        self
+    }
+
+    /// Set the moderation policy.
+    pub fn with_moderation(mut self, moderation: ModerationConfig) -> Self {
+        self.moderation = moderation;
+        self
+    }
+
+    /// Set the trust policy for remote content.
+    pub fn with_trust_policy(mut self, trust: TrustPolicy) -> Self {
+        self.trust = trust;
+        self
+    }
+
+    /// Set the query challenge configuration.
+    pub fn with_query_challenge(mut self, query_challenge: QueryChallengeConfig) -> Self {
+        self.query_challenge = query_challenge;
+        self
+    }
+
+    /// Set the limit on queries served at once.
+    pub fn with_query_limit(mut self, query_limit: QueryLimitConfig) -> Self {
+        self.query_limit = query_limit;
+        self
+    }
+
+    /// Set how refused queries are retried.
+    pub fn with_query_retry(mut self, query_retry: QueryRetryConfig) -> Self {
+        self.query_retry = query_retry;
+        self
+    }
+
+    /// Set the publisher bond configuration.
+    pub fn with_bonds(mut self, bonds: BondConfig) -> Self {
+        self.bonds = bonds;
+        self
+    }
+
+    /// Set how fraud proofs are acted on.
+    pub fn with_fraud_proofs(mut self, fraud_proofs: FraudProofConfig) -> Self {
+        self.fraud_proofs = fraud_proofs;
+        self
+    }
+
+    /// Set the automatic settlement balance top-up configuration.
+    pub fn with_top_up(mut self, top_up: TopUpConfig) -> Self {
+        self.top_up = top_up;
+        self
+    }
+
+    /// Set the data retention policy.
+    pub fn with_retention(mut self, retention: RetentionConfig) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Set the clock skew detection configuration.
+    pub fn with_clock(mut self, clock: ClockSkewConfig) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Set the peer latency and QoS configuration.
+    pub fn with_qos(mut self, qos: QosConfig) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    /// Set the content availability check configuration.
+    pub fn with_availability(mut self, availability: AvailabilityConfig) -> Self {
+        self.availability = availability;
+        self
+    }
+
+    /// Set the state snapshot configuration.
+    pub fn with_snapshot(mut self, snapshot: SnapshotConfig) -> Self {
+        self.snapshot = snapshot;
+        self
+    }
+
+    /// Set the storefront configuration.
+    pub fn with_storefront(mut self, storefront: StorefrontConfig) -> Self {
+        self.storefront = storefront;
+        self
+    }
+
+    /// Set the sync bundle configuration.
+    pub fn with_sync(mut self, sync: SyncConfig) -> Self {
+        self.sync = sync;
+        self
+    }
+
+    /// Set the re-download entitlement configuration.
+    pub fn with_entitlements(mut self, entitlements: EntitlementConfig) -> Self {
+        self.entitlements = entitlements;
+        self
+    }
+
+    /// Set the dispute watching configuration.
+    pub fn with_disputes(mut self, disputes: DisputeConfig) -> Self {
+        self.disputes = disputes;
+        self
+    }
+
+    /// Set the popularity tracking configuration.
+    pub fn with_popularity(mut self, popularity: PopularityConfig) -> Self {
+        self.popularity = popularity;
+        self
+    }
+
+    /// Set the webhook configuration.
+    pub fn with_webhooks(mut self, webhooks: WebhookConfig) -> Self {
+        self.webhooks = webhooks;
+        self
+    }
+
+    /// Set the activity digest configuration.
+    pub fn with_notifications(mut self, notifications: NotificationConfig) -> Self {
+        self.notifications = notifications;
+        self
+    }
+
+    /// Set the standing order configuration.
+    pub fn with_standing_orders(mut self, standing_orders: StandingOrderConfig) -> Self {
+        self.standing_orders = standing_orders;
+        self
+    }
+
+    /// Set how forked versions of a lineage are resolved.
+    pub fn with_fork_policy(mut self, fork_policy: ForkPolicy) -> Self {
+        self.fork_policy = fork_policy;
+        self
+    }
+
     /// Set the settlement threshold.
     pub fn with_settlement_threshold(mut self, threshold: Amount) -> Self {
         self.settlement_threshold = threshold;
@@ -143,6 +2425,18 @@ impl OpsConfig {
         self.settlement_timeout_ms = timeout_ms;
         self
     }
+
+    /// Set the group cache TTL in milliseconds.
+    pub fn with_group_cache_ttl(mut self, ttl_ms: u64) -> Self {
+        self.group_cache_ttl_ms = ttl_ms;
+        self
+    }
+
+    /// Set the attestation verification cache TTL in milliseconds.
+    pub fn with_attestation_cache_ttl(mut self, ttl_ms: u64) -> Self {
+        self.attestation_cache_ttl_ms = ttl_ms;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -201,10 +2495,401 @@ mod tests {
         let config = OpsConfig::default()
             .with_channel(ChannelConfig::new(50, 500))
             .with_settlement_threshold(10000)
-            .with_settlement_interval(3600000);
+            .with_settlement_interval(3600000)
+            .with_group_cache_ttl(60_000)
+            .with_attestation_cache_ttl(120_000);
 
         assert_eq!(config.channel.min_deposit, 50);
         assert_eq!(config.settlement_threshold, 10000);
         assert_eq!(config.settlement_interval_ms, 3600000);
+        assert_eq!(config.group_cache_ttl_ms, 60_000);
+        assert_eq!(config.attestation_cache_ttl_ms, 120_000);
+    }
+
+    #[test]
+    fn test_rebalance_config() {
+        let config = RebalanceConfig::default();
+        assert!(!config.auto_rebalance);
+        assert_eq!(config.skew_threshold, 0.8);
+
+        let config = RebalanceConfig::default()
+            .with_auto_rebalance(true)
+            .with_skew_threshold(0.3)
+            .with_min_amount(5);
+        assert!(config.auto_rebalance);
+        assert_eq!(config.skew_threshold, 0.5);
+        assert_eq!(config.min_amount, 5);
+    }
+
+    #[test]
+    fn test_auto_open_policy() {
+        let policy = AutoOpenPolicy::default();
+        assert!(policy.enabled);
+        assert!(policy.approver.is_none());
+        assert!(!AutoOpenPolicy::disabled().enabled);
+
+        let policy = AutoOpenPolicy::default()
+            .with_max_deposit_per_peer(5)
+            .with_max_total_channels(2)
+            .with_min_provider_reputation(-10)
+            .with_max_opens_per_day(1)
+            .with_approver(|req| req.deposit < 5);
+        assert_eq!(policy.max_deposit_per_peer, 5);
+        assert_eq!(policy.max_total_channels, 2);
+        assert_eq!(policy.min_provider_reputation, -10);
+        assert_eq!(policy.max_opens_per_day, 1);
+
+        let approver = policy.approver.as_ref().unwrap();
+        let request = AutoOpenRequest {
+            peer: None,
+            deposit: 4,
+            reputation: 0,
+        };
+        assert!(approver(&request));
+        assert!(format!("{:?}", policy).contains("approver: true"));
+    }
+
+    #[test]
+    fn test_close_batch_config() {
+        let config = CloseBatchConfig::default();
+        assert_eq!(config.max_concurrent, 8);
+        assert_eq!(config.peer_timeout_ms, 3_000);
+
+        let config = CloseBatchConfig::default()
+            .with_max_concurrent(0)
+            .with_peer_timeout(500);
+        assert_eq!(config.max_concurrent, 1);
+        assert_eq!(config.peer_timeout_ms, 500);
+    }
+
+    #[test]
+    fn test_search_config() {
+        let config = SearchConfig::default();
+        assert_eq!(config.max_peers, 5);
+        assert_eq!(config.peer_timeout_ms, 2_000);
+
+        let config = OpsConfig::default()
+            .with_search(
+                SearchConfig::default()
+                    .with_max_peers(2)
+                    .with_peer_timeout(100),
+            )
+            .search;
+        assert_eq!(config.max_peers, 2);
+        assert_eq!(config.peer_timeout_ms, 100);
+    }
+
+    #[test]
+    fn test_top_up_config() {
+        let config = TopUpConfig::default();
+        assert!(!config.enabled);
+        assert!(config.amount <= config.daily_cap);
+
+        let config = OpsConfig::default()
+            .with_top_up(
+                TopUpConfig::default()
+                    .with_enabled(true)
+                    .with_min_balance(5)
+                    .with_amount(20)
+                    .with_daily_cap(50)
+                    .with_check_interval(0),
+            )
+            .top_up;
+        assert!(config.enabled);
+        assert_eq!(config.min_balance, 5);
+        assert_eq!(config.amount, 20);
+        assert_eq!(config.daily_cap, 50);
+        assert_eq!(config.check_interval_secs, 1);
+    }
+
+    #[test]
+    fn test_retention_config() {
+        let config = RetentionConfig::default();
+        assert!(config.ttls.is_empty());
+        assert_eq!(config.check_interval_secs, 3600);
+
+        let config = config
+            .with_ttl(RetentionCategory::Analytics, Some(86_400))
+            .with_ttl(RetentionCategory::PeerRecords, Some(60))
+            .with_ttl(RetentionCategory::PeerRecords, None)
+            .with_check_interval(0);
+        assert_eq!(config.ttl(RetentionCategory::Analytics), Some(86_400));
+        assert_eq!(config.ttl(RetentionCategory::PeerRecords), None);
+        assert_eq!(config.check_interval_secs, 1);
+
+        let ops = OpsConfig::default().with_retention(config.clone());
+        assert_eq!(ops.retention, config);
+    }
+
+    #[test]
+    fn test_clock_skew_config() {
+        let config = ClockSkewConfig::default();
+        assert!(config.correct);
+        assert_eq!((config.min_peers, config.max_peers), (3, 8));
+
+        let config = config
+            .with_correct(false)
+            .with_tolerance_ms(1_000)
+            .with_peers(5, 2)
+            .with_probe_interval(0);
+        assert!(!config.correct);
+        assert_eq!(config.tolerance_ms, 1_000);
+        // At most as many answers are required as peers are pinged
+        assert_eq!((config.min_peers, config.max_peers), (2, 2));
+        assert_eq!(config.probe_interval_secs, 1);
+
+        let ops = OpsConfig::default().with_clock(config.clone());
+        assert_eq!(ops.clock, config);
+    }
+
+    #[test]
+    fn test_qos_config() {
+        let config = QosConfig::default();
+        assert!(config.enabled);
+        assert_eq!(config.weight, 0.5);
+
+        let config = config
+            .with_enabled(false)
+            .with_max_peers(0)
+            .with_probe_interval(0)
+            .with_weight(1.5);
+        assert!(!config.enabled);
+        assert_eq!(config.max_peers, 1);
+        assert_eq!(config.probe_interval_secs, 1);
+        assert_eq!(config.weight, 1.0);
+        assert_eq!(config.clone().with_weight(f64::NAN).weight, 0.0);
+
+        let ops = OpsConfig::default().with_qos(config.clone());
+        assert_eq!(ops.qos, config);
+    }
+
+    #[test]
+    fn test_availability_config() {
+        let config = AvailabilityConfig::default();
+        assert!(!config.enabled);
+        assert!(config.serve);
+        assert!(config.checkers.is_empty());
+
+        let checker = PeerId([3u8; 20]);
+        let config = config
+            .with_enabled(true)
+            .with_check_interval(0)
+            .with_sample_size(0)
+            .with_alert_after(0)
+            .with_checkers(vec![checker])
+            .with_serve(false);
+        assert!(config.enabled);
+        assert_eq!(config.check_interval_secs, 1);
+        assert_eq!(config.sample_size, 1);
+        assert_eq!(config.alert_after, 1);
+        assert_eq!(config.checkers, vec![checker]);
+        assert!(!config.serve);
+
+        let ops = OpsConfig::default().with_availability(config.clone());
+        assert_eq!(ops.availability, config);
+    }
+
+    #[test]
+    fn test_snapshot_config() {
+        let config = SnapshotConfig::default();
+        assert!(!config.serve);
+        assert_eq!(config.max_announcements, MAX_SNAPSHOT_ANNOUNCEMENTS);
+
+        // Limits can be lowered, never raised past the protocol's
+        let config = config.with_serve(true).with_limits(100, usize::MAX);
+        assert!(config.serve);
+        assert_eq!(
+            (config.max_announcements, config.max_peers),
+            (100, MAX_SNAPSHOT_PEERS)
+        );
+
+        let ops = OpsConfig::default().with_snapshot(config.clone());
+        assert_eq!(ops.snapshot, config);
+    }
+
+    #[test]
+    fn test_storefront_config() {
+        let config = StorefrontConfig::default();
+        assert!(!config.enabled);
+        assert!(config.featured.is_empty());
+
+        let featured = nodalync_crypto::content_hash(b"featured");
+        let config = config
+            .with_enabled(true)
+            .with_name("Field Guides")
+            .with_featured(vec![featured])
+            .with_refresh_interval(0);
+        assert_eq!(config.name, "Field Guides");
+        assert_eq!(config.featured, vec![featured]);
+        assert_eq!(config.refresh_interval_secs, 1);
+
+        let ops = OpsConfig::default().with_storefront(config.clone());
+        assert_eq!(ops.storefront, config);
+    }
+
+    #[test]
+    fn test_sync_config() {
+        let config = SyncConfig::default();
+        assert!(!config.serve);
+        assert_eq!(config.max_bundle_bytes, 1024 * 1024);
+
+        let config = config.with_serve(true).with_max_bundle_bytes(4096);
+        let ops = OpsConfig::default().with_sync(config.clone());
+        assert_eq!(ops.sync, config);
+    }
+
+    #[test]
+    fn test_entitlement_config() {
+        let config = EntitlementConfig::default();
+        assert!(config.enabled);
+        assert_eq!(config.window_ms, 2_592_000_000);
+
+        let config = config.with_enabled(false).with_window_ms(60_000);
+        let ops = OpsConfig::default().with_entitlements(config.clone());
+        assert_eq!(ops.entitlements, config);
+    }
+
+    #[test]
+    fn test_dispute_config() {
+        let config = DisputeConfig::default();
+        assert!(config.enabled);
+        assert!(config.auto_counter);
+        assert_eq!(config.warn_before_ms, 21_600_000);
+
+        let config = config
+            .with_auto_counter(false)
+            .with_warn_before_ms(60_000)
+            .with_check_interval(0);
+        assert_eq!(config.check_interval_secs, 1);
+        let ops = OpsConfig::default().with_disputes(config.clone());
+        assert_eq!(ops.disputes, config);
+    }
+
+    #[test]
+    fn test_popularity_config() {
+        let config = PopularityConfig::default();
+        assert!(config.enabled);
+        assert!(!config.fetch);
+
+        let config = config
+            .with_fetch(4)
+            .with_half_life(3_600_000)
+            .with_prewarm_interval(0);
+        assert!(config.fetch);
+        assert_eq!(config.max_fetch, 4);
+        assert_eq!(config.half_life_ms, 3_600_000);
+        assert_eq!(config.prewarm_interval_secs, 1);
+
+        let ops = OpsConfig::default().with_popularity(config.clone());
+        assert_eq!(ops.popularity, config);
+    }
+
+    #[test]
+    fn test_standing_order_config() {
+        let config = StandingOrderConfig::default();
+        assert!(!config.enabled);
+        assert!(config.inbox_dir.is_none());
+
+        let config = config
+            .with_enabled(true)
+            .with_inbox_dir("/tmp/inbox")
+            .with_max_purchases_per_run(2)
+            .with_interval(0);
+        assert!(config.enabled);
+        assert_eq!(config.inbox_dir, Some(PathBuf::from("/tmp/inbox")));
+        assert_eq!(config.max_purchases_per_run, 2);
+        assert_eq!(config.interval_secs, 1);
+
+        let ops = OpsConfig::default().with_standing_orders(config.clone());
+        assert_eq!(ops.standing_orders, config);
+    }
+
+    #[test]
+    fn test_webhook_config() {
+        let config = WebhookConfig::default();
+        assert!(!config.is_enabled());
+
+        let endpoint = WebhookEndpoint::new("https://example.com/hook", "secret")
+            .with_events(vec![WebhookEvent::PaymentReceived]);
+        assert!(endpoint.wants(WebhookEvent::PaymentReceived));
+        assert!(!endpoint.wants(WebhookEvent::ContentQueried));
+        assert!(
+            WebhookEndpoint::new("https://example.com", "s").wants(WebhookEvent::ChannelDisputed)
+        );
+
+        let config = config
+            .with_endpoint(endpoint)
+            .with_max_attempts(4)
+            .with_backoff(10, 30);
+        assert!(config.is_enabled());
+        assert_eq!(config.retry_delay_ms(1), Some(10_000));
+        assert_eq!(config.retry_delay_ms(2), Some(20_000));
+        assert_eq!(config.retry_delay_ms(3), Some(30_000));
+        assert_eq!(config.retry_delay_ms(4), None);
+
+        let ops = OpsConfig::default().with_webhooks(config.clone());
+        assert_eq!(ops.webhooks, config);
+    }
+
+    #[test]
+    fn test_notification_config() {
+        let config = NotificationConfig::default();
+        assert!(!config.is_enabled());
+        assert!(config.periods().is_empty());
+
+        let config = config
+            .with_period(DigestPeriod::Weekly, true)
+            .with_top_content(3)
+            .with_check_interval(0);
+        assert!(config.is_enabled());
+        assert_eq!(config.periods(), vec![DigestPeriod::Weekly]);
+        assert_eq!(config.check_interval_secs, 1);
+
+        let config = config.with_period(DigestPeriod::Daily, true);
+        assert_eq!(
+            config.periods(),
+            vec![DigestPeriod::Daily, DigestPeriod::Weekly]
+        );
+
+        let ops = OpsConfig::default().with_notifications(config.clone());
+        assert_eq!(ops.notifications, config);
+    }
+
+    #[test]
+    fn test_usage_report_config() {
+        let config = UsageReportConfig::default();
+        assert!(!config.send);
+        assert!(!config.accept);
+
+        let config = OpsConfig::default()
+            .with_usage_reports(
+                UsageReportConfig::default()
+                    .with_send(true)
+                    .with_accept(true)
+                    .with_max_per_peer_per_hour(5),
+            )
+            .usage_reports;
+        assert!(config.send);
+        assert!(config.accept);
+        assert_eq!(config.max_per_peer_per_hour, 5);
+    }
+
+    #[test]
+    fn test_moderation_config() {
+        let config = ModerationConfig::default();
+        assert!(config.accept_reports);
+        assert_eq!(config.auto_hide_threshold, None);
+
+        let config = OpsConfig::default()
+            .with_moderation(
+                ModerationConfig::default()
+                    .with_accept_reports(false)
+                    .with_auto_hide_threshold(3)
+                    .with_min_reporter_reputation(10),
+            )
+            .moderation;
+        assert!(!config.accept_reports);
+        assert_eq!(config.auto_hide_threshold, Some(3));
+        assert_eq!(config.min_reporter_reputation, 10);
     }
 }
@@ -0,0 +1,447 @@
+//! Validation and TOML loading of the operations configuration.
+//!
+//! [`OpsConfig`] is built with `with_*` setters on the defaults, or read
+//! from a TOML file whose tables mirror its fields (`[channel]`, `[trust]`,
+//! `[announcement_ingest]`, ...). Omitted fields keep their defaults and
+//! unknown fields are rejected. Either way, [`OpsConfig::validated`] checks
+//! that the settings fit together before the node uses them.
+
+use std::path::Path;
+
+use nodalync_types::{MAX_APP_FEE_BASIS_POINTS, MAX_STOREFRONT_FEATURED, MAX_TITLE_LENGTH};
+use nodalync_valid::validate_quota_policy;
+use regex::Regex;
+
+use crate::config::OpsConfig;
+use crate::error::{OpsError, OpsResult};
+
+impl OpsConfig {
+    /// Check that the settings are consistent.
+    ///
+    /// Every problem found is listed in the returned
+    /// [`OpsError::InvalidConfig`].
+    pub fn validate(&self) -> OpsResult<()> {
+        let mut problems = Vec::new();
+        let mut check = |ok: bool, problem: &str| {
+            if !ok {
+                problems.push(problem.to_string());
+            }
+        };
+
+        let channel = &self.channel;
+        check(
+            channel.min_deposit > 0,
+            "channel.min_deposit must be positive",
+        );
+        check(
+            channel.default_deposit >= channel.min_deposit,
+            "channel.default_deposit is below channel.min_deposit",
+        );
+        check(
+            channel.max_accept_deposit >= channel.min_deposit,
+            "channel.max_accept_deposit is below channel.min_deposit",
+        );
+        check(
+            !channel.auto_deposit_on_channel_open || channel.auto_deposit_amount > 0,
+            "channel.auto_deposit_amount must be positive when auto-deposit is on",
+        );
+        check(
+            (0.5..=1.0).contains(&self.rebalance.skew_threshold),
+            "rebalance.skew_threshold must be between 0.5 and 1.0",
+        );
+
+        let auto_open = &self.auto_open;
+        check(
+            !auto_open.enabled || auto_open.max_deposit_per_peer >= channel.min_deposit,
+            "auto_open.max_deposit_per_peer is below channel.min_deposit",
+        );
+        check(
+            !auto_open.enabled || auto_open.max_total_channels > 0,
+            "auto_open.max_total_channels must be positive when auto-open is on",
+        );
+
+        let top_up = &self.top_up;
+        check(
+            !top_up.enabled || top_up.amount > 0,
+            "top_up.amount must be positive when top-ups are on",
+        );
+        check(
+            !top_up.enabled || top_up.daily_cap >= top_up.amount,
+            "top_up.daily_cap is below top_up.amount",
+        );
+
+        let clock = &self.clock;
+        check(
+            clock.min_peers >= 1 && clock.min_peers <= clock.max_peers,
+            "clock.min_peers must be between 1 and clock.max_peers",
+        );
+        check(
+            self.popularity.half_life_ms > 0,
+            "popularity.half_life_ms must be positive",
+        );
+        check(
+            self.popularity.min_score.is_finite() && self.popularity.min_score >= 0.0,
+            "popularity.min_score must be a non-negative number",
+        );
+        check(
+            self.recommendation.min_score.is_finite(),
+            "recommendation.min_score must be a number",
+        );
+        check(
+            self.entitlements.window_ms > 0,
+            "entitlements.window_ms must be positive",
+        );
+
+        // Job intervals and timeouts
+        for (interval, name) in [
+            (top_up.check_interval_secs, "top_up.check_interval_secs"),
+            (
+                self.retention.check_interval_secs,
+                "retention.check_interval_secs",
+            ),
+            (clock.probe_interval_secs, "clock.probe_interval_secs"),
+            (
+                self.popularity.prewarm_interval_secs,
+                "popularity.prewarm_interval_secs",
+            ),
+            (clock.ping_timeout_ms, "clock.ping_timeout_ms"),
+            (
+                self.close_batch.peer_timeout_ms,
+                "close_batch.peer_timeout_ms",
+            ),
+            (self.search.peer_timeout_ms, "search.peer_timeout_ms"),
+            (
+                self.query_limit.retry_after_ms,
+                "query_limit.retry_after_ms",
+            ),
+            (
+                self.webhooks.delivery_interval_secs,
+                "webhooks.delivery_interval_secs",
+            ),
+            (self.webhooks.timeout_ms, "webhooks.timeout_ms"),
+            (
+                self.notifications.check_interval_secs,
+                "notifications.check_interval_secs",
+            ),
+            (
+                self.standing_orders.interval_secs,
+                "standing_orders.interval_secs",
+            ),
+            (
+                self.disputes.check_interval_secs,
+                "disputes.check_interval_secs",
+            ),
+            (
+                self.storefront.refresh_interval_secs,
+                "storefront.refresh_interval_secs",
+            ),
+            (self.settlement_interval_ms, "settlement_interval_ms"),
+            (self.settlement_timeout_ms, "settlement_timeout_ms"),
+        ] {
+            check(interval > 0, &format!("{} must be positive", name));
+        }
+
+        // Queue and batch sizes
+        check(
+            self.close_batch.max_concurrent > 0,
+            "close_batch.max_concurrent must be positive",
+        );
+        check(
+            self.query_limit.max_concurrent > 0,
+            "query_limit.max_concurrent must be positive",
+        );
+        check(
+            self.announcement_ingest.batch_size > 0,
+            "announcement_ingest.batch_size must be positive",
+        );
+        check(
+            self.announcement_ingest.max_pending > 0,
+            "announcement_ingest.max_pending must be positive",
+        );
+
+        let filter = &self.announcement_filter;
+        check(
+            filter.min_price <= filter.max_price,
+            "announcement_filter.min_price is above announcement_filter.max_price",
+        );
+        for pattern in &filter.title_patterns {
+            check(
+                Regex::new(pattern).is_ok(),
+                &format!(
+                    "announcement_filter.title_patterns: bad pattern {:?}",
+                    pattern
+                ),
+            );
+        }
+
+        let webhooks = &self.webhooks;
+        check(
+            webhooks.max_attempts > 0 && webhooks.batch_size > 0,
+            "webhooks.max_attempts and webhooks.batch_size must be positive",
+        );
+        check(
+            webhooks.initial_backoff_secs <= webhooks.max_backoff_secs,
+            "webhooks.initial_backoff_secs is above webhooks.max_backoff_secs",
+        );
+        for endpoint in &webhooks.endpoints {
+            check(
+                endpoint.url.starts_with("https://") || endpoint.url.starts_with("http://"),
+                &format!("webhooks.endpoints: bad URL {:?}", endpoint.url),
+            );
+            check(
+                !endpoint.secret.is_empty(),
+                &format!("webhooks.endpoints: {} has no secret", endpoint.url),
+            );
+        }
+
+        check(
+            self.trust.flag_below <= 100 && self.trust.reject_below <= 100,
+            "trust.flag_below and trust.reject_below must be at most 100",
+        );
+        if let Some(fee) = &self.app_fee {
+            check(
+                fee.basis_points > 0 && fee.basis_points <= MAX_APP_FEE_BASIS_POINTS,
+                &format!(
+                    "app_fee.basis_points must be between 1 and {}",
+                    MAX_APP_FEE_BASIS_POINTS
+                ),
+            );
+        }
+        check(
+            self.storefront.featured.len() <= MAX_STOREFRONT_FEATURED,
+            &format!(
+                "storefront.featured may list at most {} items",
+                MAX_STOREFRONT_FEATURED
+            ),
+        );
+        check(
+            self.storefront.name.chars().count() <= MAX_TITLE_LENGTH,
+            &format!(
+                "storefront.name must be at most {} characters",
+                MAX_TITLE_LENGTH
+            ),
+        );
+        if let Some(quota) = &self.quota {
+            check(
+                validate_quota_policy(quota).is_ok(),
+                "quota must set a non-zero queries or bytes limit",
+            );
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(OpsError::invalid_config(problems.join("; ")))
+        }
+    }
+
+    /// Finish a `with_*` chain, returning the configuration if it is valid.
+    pub fn validated(self) -> OpsResult<Self> {
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Parse and validate a TOML configuration.
+    pub fn from_toml_str(toml: &str) -> OpsResult<Self> {
+        let config: Self =
+            toml::from_str(toml).map_err(|e| OpsError::invalid_config(e.to_string()))?;
+        config.validated()
+    }
+
+    /// Read, parse and validate a TOML configuration file.
+    pub fn load(path: &Path) -> OpsResult<Self> {
+        let toml = std::fs::read_to_string(path).map_err(|e| {
+            OpsError::invalid_config(format!("cannot read {}: {}", path.display(), e))
+        })?;
+        Self::from_toml_str(&toml)
+    }
+
+    /// Apply TOML overrides on top of this configuration.
+    ///
+    /// Tables merge field by field, so `[channel] min_deposit = 1` leaves
+    /// the other channel settings as they were. The auto-open approver is
+    /// kept. The result is validated.
+    pub fn merge_toml(self, overrides: toml::Table) -> OpsResult<Self> {
+        let mut table = self.to_toml_table()?;
+        merge_tables(&mut table, overrides);
+        let approver = self.auto_open.approver;
+        let mut config: Self = table
+            .try_into()
+            .map_err(|e: toml::de::Error| OpsError::invalid_config(e.to_string()))?;
+        config.auto_open.approver = approver;
+        config.validated()
+    }
+
+    /// This configuration as TOML.
+    pub fn to_toml_string(&self) -> OpsResult<String> {
+        toml::to_string(self).map_err(|e| OpsError::invalid_config(e.to_string()))
+    }
+
+    /// The default configuration as TOML, for documentation.
+    ///
+    /// Settings that are off by default (`Option` fields such as
+    /// `app_fee` or `trust.max_price`) are not listed.
+    pub fn defaults_toml() -> String {
+        Self::default()
+            .to_toml_string()
+            .expect("default config serializes")
+    }
+
+    fn to_toml_table(&self) -> OpsResult<toml::Table> {
+        toml::Table::try_from(self).map_err(|e| OpsError::invalid_config(e.to_string()))
+    }
+}
+
+/// Merge `overrides` into `base`, recursing into tables present in both.
+fn merge_tables(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(value)) => {
+                merge_tables(base, value)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ChannelConfig, ForkPolicy, RebalanceConfig, WebhookEvent};
+    use crate::TrustCheck;
+
+    #[test]
+    fn test_default_config_is_valid() {
+        OpsConfig::default().validate().unwrap();
+        assert!(OpsConfig::default().validated().is_ok());
+    }
+
+    #[test]
+    fn test_validate_lists_every_problem() {
+        let mut config = OpsConfig::default()
+            .with_channel(ChannelConfig::new(100, 50))
+            .with_settlement_timeout(0);
+        config.rebalance = RebalanceConfig {
+            skew_threshold: 0.2,
+            ..RebalanceConfig::default()
+        };
+        config.announcement_filter.title_patterns = vec!["(".to_string()];
+
+        let err = config.validate().unwrap_err();
+        assert!(matches!(err, OpsError::InvalidConfig(_)));
+        let message = err.to_string();
+        assert!(message.contains("channel.default_deposit"));
+        assert!(message.contains("rebalance.skew_threshold"));
+        assert!(message.contains("settlement_timeout_ms"));
+        assert!(message.contains("title_patterns"));
+    }
+
+    #[test]
+    fn test_from_toml_keeps_defaults_for_omitted_fields() {
+        let config = OpsConfig::from_toml_str(
+            r#"
+            settlement_timeout_ms = 5000
+
+            [channel]
+            min_deposit = 10
+
+            [trust]
+            reject_on = ["attestation", "license"]
+            "#,
+        )
+        .unwrap();
+
+        let defaults = OpsConfig::default();
+        assert_eq!(config.settlement_timeout_ms, 5000);
+        assert_eq!(config.channel.min_deposit, 10);
+        assert_eq!(
+            config.channel.default_deposit,
+            defaults.channel.default_deposit
+        );
+        assert_eq!(
+            config.trust.reject_on,
+            vec![TrustCheck::Attestation, TrustCheck::License]
+        );
+        assert_eq!(config.search.max_peers, defaults.search.max_peers);
+    }
+
+    #[test]
+    fn test_from_toml_rejects_unknown_and_invalid_settings() {
+        let err = OpsConfig::from_toml_str("[channel]\nmin_deposti = 10\n").unwrap_err();
+        assert!(err.to_string().contains("min_deposti"));
+
+        let err = OpsConfig::from_toml_str("[announcement_ingest]\nbatch_size = 0\n").unwrap_err();
+        assert!(err.to_string().contains("announcement_ingest.batch_size"));
+    }
+
+    #[test]
+    fn test_merge_toml_overrides_single_fields() {
+        let base = OpsConfig::default().with_settlement_timeout(7000);
+        let overrides: toml::Table = toml::from_str("[clock]\ncorrect = true\n").unwrap();
+
+        let config = base.merge_toml(overrides).unwrap();
+        assert!(config.clock.correct);
+        assert_eq!(config.clock.max_peers, OpsConfig::default().clock.max_peers);
+        assert_eq!(config.settlement_timeout_ms, 7000);
+    }
+
+    #[test]
+    fn test_fork_policy_from_toml() {
+        let config = OpsConfig::from_toml_str("fork_policy = \"owner_canonical\"\n").unwrap();
+        assert_eq!(config.fork_policy, ForkPolicy::OwnerCanonical);
+        assert_eq!(
+            OpsConfig::default().fork_policy,
+            ForkPolicy::LatestTimestamp
+        );
+    }
+
+    #[test]
+    fn test_webhooks_from_toml() {
+        let config = OpsConfig::from_toml_str(
+            r#"
+            [[webhooks.endpoints]]
+            url = "https://hooks.example.com/nodalync"
+            events = ["payment_received", "settlement_confirmed"]
+            secret = "s3cret"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.webhooks.endpoints.len(), 1);
+        assert_eq!(
+            config.webhooks.endpoints[0].events,
+            vec![
+                WebhookEvent::PaymentReceived,
+                WebhookEvent::SettlementConfirmed
+            ]
+        );
+
+        let err = OpsConfig::from_toml_str(
+            "[[webhooks.endpoints]]\nurl = \"ftp://example.com\"\nsecret = \"\"\n",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("bad URL"));
+        assert!(err.contains("has no secret"));
+    }
+
+    #[test]
+    fn test_defaults_toml_round_trips() {
+        let toml = OpsConfig::defaults_toml();
+        assert!(toml.contains("[channel]"));
+        let config = OpsConfig::from_toml_str(&toml).unwrap();
+        assert_eq!(config.to_toml_string().unwrap(), toml);
+    }
+
+    #[test]
+    fn test_load_reads_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ops.toml");
+        std::fs::write(&path, "max_preview_mentions = 9\n").unwrap();
+        assert_eq!(OpsConfig::load(&path).unwrap().max_preview_mentions, 9);
+
+        let err = OpsConfig::load(&dir.path().join("missing.toml")).unwrap_err();
+        assert!(matches!(err, OpsError::InvalidConfig(_)));
+    }
+}
@@ -4,8 +4,11 @@
 //! as specified in Protocol Specification §7.1.
 
 use nodalync_crypto::{content_hash, Hash, Timestamp};
-use nodalync_store::{CacheStore, ContentStore, ManifestStore, ProvenanceGraph};
-use nodalync_types::{ContentType, Manifest, Metadata, Provenance, Version, Visibility};
+use nodalync_store::delta::encode_delta;
+use nodalync_store::{CacheStore, ContentStore, DeltaStore, ManifestStore, ProvenanceGraph};
+use nodalync_types::{
+    normalize_tags, ChunkTree, ContentType, Manifest, Metadata, Provenance, Version, Visibility,
+};
 use nodalync_valid::Validator;
 
 use crate::error::{OpsError, OpsResult};
@@ -27,18 +30,21 @@ where
     /// 5. Creates Manifest
     /// 6. Validates content
     /// 7. Stores content and manifest
-    pub fn create_content(&mut self, content: &[u8], metadata: Metadata) -> OpsResult<Hash> {
+    pub fn create_content(&self, content: &[u8], metadata: Metadata) -> OpsResult<Hash> {
         let timestamp = current_timestamp();
         self.create_content_with_timestamp(content, metadata, timestamp)
     }
 
     /// Create content with a specific timestamp (for testing).
     pub fn create_content_with_timestamp(
-        &mut self,
+        &self,
         content: &[u8],
-        metadata: Metadata,
+        mut metadata: Metadata,
         timestamp: Timestamp,
     ) -> OpsResult<Hash> {
+        metadata.tags = normalize_tags(&metadata.tags);
+        metadata.chunks = ChunkTree::for_content(content);
+
         // 1. Compute content hash
         let hash = content_hash(content);
 
@@ -67,10 +73,12 @@ where
         self.validator.validate_content(content, &manifest)?;
         self.validator.validate_version(&manifest, None)?;
         self.validator.validate_provenance(&manifest, &[])?;
+        self.validate_metadata_fields(&manifest.metadata)?;
 
         // 7. Store content and manifest
         self.state.content.store_verified(&hash, content)?;
         self.state.manifests.store(&manifest)?;
+        self.register_tags(&manifest.metadata.tags)?;
 
         // Also add to provenance graph
         self.state.provenance.add(&hash, &[])?;
@@ -84,9 +92,10 @@ where
     /// 1. Computes new hash
     /// 2. Links version (previous, root from previous.root)
     /// 3. Inherits visibility
-    /// 4. Stores
+    /// 4. Stores, plus a delta from the previous version when it is smaller
+    ///    than the new content
     pub fn update_content(
-        &mut self,
+        &self,
         old_hash: &Hash,
         new_content: &[u8],
         new_metadata: Metadata,
@@ -97,12 +106,15 @@ where
 
     /// Update content with a specific timestamp (for testing).
     pub fn update_content_with_timestamp(
-        &mut self,
+        &self,
         old_hash: &Hash,
         new_content: &[u8],
-        new_metadata: Metadata,
+        mut new_metadata: Metadata,
         timestamp: Timestamp,
     ) -> OpsResult<Hash> {
+        new_metadata.tags = normalize_tags(&new_metadata.tags);
+        new_metadata.chunks = ChunkTree::for_content(new_content);
+
         // Load the previous manifest
         let old_manifest = self
             .state
@@ -129,11 +141,13 @@ where
                     entry.hash = new_hash;
                 }
             }
+            // The attestation was of the old hash
+            prov.attested = false;
             prov
         };
 
         // Create new manifest inheriting visibility
-        let new_manifest = Manifest {
+        let mut new_manifest = Manifest {
             hash: new_hash,
             content_type: old_manifest.content_type,
             owner: self.peer_id(),
@@ -150,19 +164,45 @@ where
         // Validate
         self.validator
             .validate_content(new_content, &new_manifest)?;
-        self.validator
-            .validate_version(&new_manifest, Some(&old_manifest))?;
+        self.validate_metadata_fields(&new_manifest.metadata)?;
+        self.flag_version_forks(&mut new_manifest, Some(&old_manifest))?;
 
         // Store
         self.state.content.store_verified(&new_hash, new_content)?;
         self.state.manifests.store(&new_manifest)?;
+        self.register_tags(&new_manifest.metadata.tags)?;
 
         // Update provenance graph
         self.state.provenance.add(&new_hash, &[*old_hash])?;
 
+        self.store_version_delta(old_hash, &new_hash, new_content);
+
         Ok(new_hash)
     }
 
+    /// Store a delta from a previous version to its successor.
+    ///
+    /// Best effort: the full blob is already stored, so a delta that fails
+    /// to encode or isn't smaller than the content is simply skipped.
+    fn store_version_delta(&self, old_hash: &Hash, new_hash: &Hash, new_content: &[u8]) {
+        let base = match self.state.content.load(old_hash) {
+            Ok(Some(base)) => base,
+            _ => return,
+        };
+
+        match encode_delta(&base, new_content) {
+            Ok(delta) if delta.len() < new_content.len() => {
+                if let Err(e) = self.state.content.store_delta(old_hash, new_hash, &delta) {
+                    tracing::warn!(base = %old_hash, target = %new_hash, error = %e, "Failed to store version delta");
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!(base = %old_hash, target = %new_hash, error = %e, "Failed to encode version delta");
+            }
+        }
+    }
+
     /// Derive new content from sources.
     ///
     /// Spec §7.1.5:
@@ -174,7 +214,7 @@ where
     /// 6. Validates provenance
     /// 7. Stores
     pub fn derive_content(
-        &mut self,
+        &self,
         sources: &[Hash],
         insight: &[u8],
         metadata: Metadata,
@@ -185,12 +225,14 @@ where
 
     /// Derive content with a specific timestamp (for testing).
     pub fn derive_content_with_timestamp(
-        &mut self,
+        &self,
         sources: &[Hash],
         insight: &[u8],
-        metadata: Metadata,
+        mut metadata: Metadata,
         timestamp: Timestamp,
     ) -> OpsResult<Hash> {
+        metadata.tags = normalize_tags(&metadata.tags);
+        metadata.chunks = ChunkTree::for_content(insight);
         if sources.is_empty() {
             return Err(OpsError::invalid_operation(
                 "derive requires at least one source",
@@ -270,10 +312,12 @@ where
         self.validator
             .validate_provenance(&manifest, &source_manifests)?;
         self.validator.validate_content(insight, &manifest)?;
+        self.validate_metadata_fields(&manifest.metadata)?;
 
         // 7. Store
         self.state.content.store_verified(&hash, insight)?;
         self.state.manifests.store(&manifest)?;
+        self.register_tags(&manifest.metadata.tags)?;
         self.state.provenance.add(&hash, sources)?;
 
         Ok(hash)
@@ -285,14 +329,14 @@ where
     /// 1. Verifies L3 was queried (in cache)
     /// 2. Verifies content_type is L3
     /// 3. Stores reference as new L0
-    pub fn reference_l3_as_l0(&mut self, l3_hash: &Hash) -> OpsResult<Hash> {
+    pub fn reference_l3_as_l0(&self, l3_hash: &Hash) -> OpsResult<Hash> {
         let timestamp = current_timestamp();
         self.reference_l3_as_l0_with_timestamp(l3_hash, timestamp)
     }
 
     /// Reference L3 as L0 with a specific timestamp (for testing).
     pub fn reference_l3_as_l0_with_timestamp(
-        &mut self,
+        &self,
         l3_hash: &Hash,
         timestamp: Timestamp,
     ) -> OpsResult<Hash> {
@@ -379,7 +423,7 @@ mod tests {
 
     #[test]
     fn test_create_content() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let content = b"Hello, Nodalync!";
         let metadata = Metadata::new("Test", content.len() as u64);
 
@@ -400,7 +444,7 @@ mod tests {
 
     #[test]
     fn test_update_content() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create initial content
         let content1 = b"Version 1";
@@ -419,9 +463,44 @@ mod tests {
         assert_eq!(manifest2.version.root, hash1); // Root is the original v1 hash
     }
 
+    #[test]
+    fn test_update_stores_delta() {
+        let (ops, _temp) = create_test_ops();
+
+        let content1: Vec<u8> = (0..500)
+            .flat_map(|i| format!("Line {} of the first version.\n", i).into_bytes())
+            .collect();
+        let metadata1 = Metadata::new("Doc v1", content1.len() as u64);
+        let hash1 = ops
+            .create_content_with_timestamp(&content1, metadata1, 1000)
+            .unwrap();
+
+        let mut content2 = content1.clone();
+        content2.extend_from_slice(b"An appended line.\n");
+        let metadata2 = Metadata::new("Doc v2", content2.len() as u64);
+        let hash2 = ops
+            .update_content_with_timestamp(&hash1, &content2, metadata2, 2000)
+            .unwrap();
+
+        let delta = ops
+            .state
+            .content
+            .load_delta(&hash1, &hash2)
+            .unwrap()
+            .unwrap();
+        assert!(delta.len() < content2.len());
+        let rebuilt =
+            nodalync_store::delta::apply_delta(&content1, &delta, content2.len() as u64).unwrap();
+        assert_eq!(rebuilt, content2);
+
+        // Full blobs are still kept
+        assert!(ops.state.content.exists(&hash1));
+        assert!(ops.state.content.exists(&hash2));
+    }
+
     #[test]
     fn test_derive_content() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create two source contents
         let source1 = b"Source document 1";
@@ -447,7 +526,7 @@ mod tests {
 
     #[test]
     fn test_derive_requires_queried_sources() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Try to derive from non-existent source
         let fake_hash = content_hash(b"nonexistent");
@@ -460,7 +539,7 @@ mod tests {
 
     #[test]
     fn test_reference_l3_as_l0() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create source and derive L3
         let source = b"Source content";
@@ -484,7 +563,7 @@ mod tests {
 
     #[test]
     fn test_reference_requires_l3() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create L0 content
         let content = b"L0 content";
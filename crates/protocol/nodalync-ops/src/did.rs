@@ -0,0 +1,211 @@
+//! DIDs for Nodalync identities.
+//!
+//! Every identity has a `did:key` identifier (see
+//! [`nodalync_crypto::did_from_public_key`]) so it can be used with
+//! verifiable-credential tooling. Our own DID document is exported from
+//! the node's key; DIDs and DID documents from other peers are resolved
+//! without a registry, since a `did:key` identifier carries its key, and
+//! are matched against the peer store. Peer info advertising a DID is
+//! only accepted when the DID is that of the peer's own key.
+
+use nodalync_crypto::{
+    peer_id_from_public_key, peer_id_to_string, public_key_from_did, PeerId, PublicKey,
+};
+use nodalync_store::{PeerInfo, PeerStore};
+use nodalync_types::DidDocument;
+use nodalync_valid::{validate_did_document, validate_peer_info, ValidationError, Validator};
+use nodalync_wire::PeerInfoPayload;
+use tracing::debug;
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+/// A DID resolved to its document and peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DidResolution {
+    /// The DID document
+    pub document: DidDocument,
+    /// Public key of the DID subject
+    pub public_key: PublicKey,
+    /// PeerId of the DID subject
+    pub peer_id: PeerId,
+    /// What the peer store knows about the peer, if it has been seen
+    pub peer: Option<PeerInfo>,
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Get our own DID document.
+    ///
+    /// Uses the loaded private key, or the identity store's public key
+    /// when no private key is loaded.
+    pub fn did_document(&self) -> OpsResult<DidDocument> {
+        let public_key = match self.private_key() {
+            Some(private_key) => private_key.public_key(),
+            None => self.state.identity.public_key()?,
+        };
+        Ok(DidDocument::new(&public_key))
+    }
+
+    /// Resolve a `did:key` identifier to its DID document and peer.
+    pub fn resolve_did(&self, did: &str) -> OpsResult<DidResolution> {
+        let public_key = public_key_from_did(did).map_err(|e| invalid_did(e.to_string()))?;
+        self.resolution(DidDocument::new(&public_key), public_key)
+    }
+
+    /// Verify a DID document received from another peer.
+    ///
+    /// The document must be a valid `did:key` document (see
+    /// [`validate_did_document`]) and, when `expected` is given, must be
+    /// the DID of that peer.
+    pub fn verify_did_document(
+        &self,
+        document: &DidDocument,
+        expected: Option<&PeerId>,
+    ) -> OpsResult<DidResolution> {
+        let public_key = validate_did_document(document)?;
+
+        if let Some(expected) = expected {
+            if peer_id_from_public_key(&public_key) != *expected {
+                return Err(invalid_did(format!(
+                    "DID belongs to {}, not {}",
+                    peer_id_to_string(&peer_id_from_public_key(&public_key)),
+                    peer_id_to_string(expected)
+                )));
+            }
+        }
+
+        self.resolution(document.clone(), public_key)
+    }
+
+    /// Handle peer info received from a peer.
+    ///
+    /// Validates that the PeerId and the advertised DID (if any) belong to
+    /// the peer's key, then records the key, addresses and capabilities in
+    /// the peer store.
+    pub fn handle_peer_info(&self, payload: &PeerInfoPayload) -> OpsResult<()> {
+        validate_peer_info(payload)?;
+
+        let now = current_timestamp();
+        let peer = match self.state.peers.get(&payload.peer_id)? {
+            Some(mut peer) => {
+                peer.public_key = payload.public_key;
+                for address in &payload.addresses {
+                    peer.add_address(address.clone());
+                }
+                peer.touch(now);
+                peer.capabilities = Some(payload.capabilities.clone());
+                peer
+            }
+            None => PeerInfo::new(
+                payload.peer_id,
+                payload.public_key,
+                payload.addresses.clone(),
+                now,
+            )
+            .with_capabilities(payload.capabilities.clone()),
+        };
+        self.state.peers.upsert(&peer)?;
+
+        debug!(peer = %payload.peer_id, did = %peer.did(), "Recorded peer info");
+        Ok(())
+    }
+
+    fn resolution(&self, document: DidDocument, public_key: PublicKey) -> OpsResult<DidResolution> {
+        let peer_id = peer_id_from_public_key(&public_key);
+        Ok(DidResolution {
+            peer: self.state.peers.get(&peer_id)?,
+            document,
+            public_key,
+            peer_id,
+        })
+    }
+}
+
+fn invalid_did(reason: impl Into<String>) -> OpsError {
+    OpsError::Validation(ValidationError::InvalidDid {
+        reason: reason.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{did_from_public_key, generate_identity};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, PublicKey, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops =
+            DefaultNodeOperations::with_defaults(state, peer_id_from_public_key(&public_key));
+        ops.set_private_key(private_key);
+        (ops, public_key, temp_dir)
+    }
+
+    #[test]
+    fn test_did_document_export() {
+        let (ops, public_key, _temp) = create_test_ops();
+        let doc = ops.did_document().unwrap();
+        assert_eq!(doc.id, did_from_public_key(&public_key));
+
+        let resolved = ops.verify_did_document(&doc, Some(&ops.peer_id())).unwrap();
+        assert_eq!(resolved.public_key, public_key);
+        assert_eq!(resolved.peer_id, ops.peer_id());
+    }
+
+    #[test]
+    fn test_resolve_and_verify_peer_dids() {
+        let (ops, _, _temp) = create_test_ops();
+        let (_, peer_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&peer_key);
+        let did = did_from_public_key(&peer_key);
+
+        // Unknown peers still resolve
+        let resolved = ops.resolve_did(&did).unwrap();
+        assert_eq!(resolved.peer_id, peer_id);
+        assert_eq!(resolved.document, DidDocument::new(&peer_key));
+        assert!(resolved.peer.is_none());
+
+        // Peer info advertising the DID records the peer
+        let mut info = PeerInfoPayload {
+            peer_id,
+            public_key: peer_key,
+            addresses: vec!["/ip4/10.0.0.1/tcp/9000".to_string()],
+            capabilities: vec![],
+            content_count: 0,
+            uptime: 0,
+            did: Some(did.clone()),
+        };
+        ops.handle_peer_info(&info).unwrap();
+        let resolved = ops.resolve_did(&did).unwrap();
+        let peer = resolved.peer.unwrap();
+        assert_eq!(peer.addresses, info.addresses);
+        assert_eq!(peer.did(), did);
+
+        // Documents must belong to the expected peer
+        let doc = DidDocument::new(&peer_key);
+        assert!(ops.verify_did_document(&doc, Some(&peer_id)).is_ok());
+        assert!(ops.verify_did_document(&doc, Some(&ops.peer_id())).is_err());
+
+        // Peer info claiming another key's DID is rejected
+        let (_, other_key) = generate_identity();
+        info.did = Some(did_from_public_key(&other_key));
+        assert!(matches!(
+            ops.handle_peer_info(&info),
+            Err(OpsError::Validation(ValidationError::InvalidDid { .. }))
+        ));
+
+        assert!(matches!(
+            ops.resolve_did("did:web:example.com"),
+            Err(OpsError::Validation(ValidationError::InvalidDid { .. }))
+        ));
+    }
+}
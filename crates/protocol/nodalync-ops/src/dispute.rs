@@ -0,0 +1,609 @@
+//! Channel dispute tracking, evidence and counter-disputes.
+//!
+//! A dispute puts a claimed channel state on-chain; the counterparty has
+//! until the end of the dispute period to submit a state with a higher
+//! nonce, and the highest one is settled. This module covers our side of
+//! that lifecycle:
+//!
+//! - [`check_disputes`](NodeOperations::check_disputes) looks up each open
+//!   channel and tracked dispute on-chain, records disputes and their
+//!   deadlines, reports new ones and those whose deadline is near, and with
+//!   `DisputeConfig::auto_counter` counters a stale state with our latest
+//! - [`counter_dispute`](NodeOperations::counter_dispute) does the same for
+//!   one channel on demand
+//! - [`dispute_evidence`](NodeOperations::dispute_evidence) packages what
+//!   we hold against a counterparty as a CHANNEL_DISPUTE payload: our
+//!   latest signed state as the claim, with earlier signed states, the
+//!   channel's unsettled payments, the receipts the counterparty signed for
+//!   our purchases and fraud proofs against it as evidence
+
+use nodalync_crypto::{PeerId, PrivateKey};
+use nodalync_settle::{ChannelId, OnChainDispute, Settlement};
+use nodalync_store::{ChannelStore, DisputeRecord, DisputeStore, FraudProofStore, LibraryStore};
+use nodalync_types::Channel;
+use nodalync_valid::{sign_channel_close, Validator};
+use nodalync_wire::{
+    encode_payload, ChannelBalances, ChannelDisputePayload, ChannelUpdatePayload, DisputeEvidence,
+};
+use tracing::{info, warn};
+
+use crate::error::{OpsError, OpsResult};
+use crate::events::OpsEvent;
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Check the disputes on our channels.
+    ///
+    /// Returns the disputes still open afterwards, or `None` when dispute
+    /// watching is disabled or settlement is not configured. Emits
+    /// `DisputeOpened`, `DisputeCountered` and
+    /// `DisputeDeadlineApproaching`. A channel that can't be looked up is
+    /// skipped until the next check.
+    pub async fn check_disputes(
+        &self,
+        private_key: &PrivateKey,
+    ) -> OpsResult<Option<Vec<DisputeRecord>>> {
+        let config = self.config.disputes.clone();
+        if !config.enabled {
+            return Ok(None);
+        }
+        let Some(settlement) = self.settlement().cloned() else {
+            return Ok(None);
+        };
+
+        // Open channels may have been disputed by the counterparty; tracked
+        // disputes may have been resolved
+        let mut channels: Vec<Channel> = self
+            .state
+            .channels
+            .list_open()?
+            .into_iter()
+            .map(|(_, channel)| channel)
+            .collect();
+        for tracked in self.state.disputes.list(false)? {
+            if channels.iter().all(|c| c.channel_id != tracked.channel_id) {
+                if let Some(channel) = self.state.channels.get(&tracked.peer)? {
+                    channels.push(channel);
+                }
+            }
+        }
+
+        for channel in channels {
+            let channel_id = ChannelId::new(channel.channel_id);
+            let on_chain = match settlement.get_dispute(&channel_id).await {
+                Ok(on_chain) => on_chain,
+                Err(e) => {
+                    warn!(channel_id = %channel.channel_id, error = %e, "Dispute lookup failed");
+                    continue;
+                }
+            };
+            let tracked = self.state.disputes.get(&channel.channel_id)?;
+
+            match (on_chain, tracked) {
+                (None, None) => {}
+                (None, Some(mut record)) => {
+                    record.closed_at = Some(current_timestamp());
+                    self.state.disputes.track(&record)?;
+                    info!(channel_id = %channel.channel_id, "Channel dispute is over");
+                }
+                (Some(dispute), tracked) => {
+                    self.follow_dispute(
+                        settlement.as_ref(),
+                        &channel,
+                        &dispute,
+                        tracked,
+                        private_key,
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        Ok(Some(self.state.disputes.list(false)?))
+    }
+
+    /// Counter the dispute on our channel with a peer with our latest
+    /// signed state.
+    ///
+    /// Fails if the channel isn't disputed on-chain, if the dispute period
+    /// has ended, or if the chain already holds a state at least as recent
+    /// as ours. Returns the counter-dispute transaction ID.
+    pub async fn counter_dispute(
+        &self,
+        peer: &PeerId,
+        private_key: &PrivateKey,
+    ) -> OpsResult<String> {
+        let channel = self
+            .state
+            .channels
+            .get(peer)?
+            .ok_or(OpsError::ChannelNotFound)?;
+        let settlement = self
+            .settlement()
+            .cloned()
+            .ok_or_else(|| OpsError::invalid_operation("settlement layer required for disputes"))?;
+        let dispute = settlement
+            .get_dispute(&ChannelId::new(channel.channel_id))
+            .await
+            .map_err(|e| OpsError::SettlementFailed(e.to_string()))?
+            .ok_or_else(|| OpsError::invalid_operation("channel is not disputed"))?;
+
+        let tracked = self.state.disputes.get(&channel.channel_id)?;
+        let mut record = tracked.unwrap_or_else(|| new_record(&channel, &dispute));
+        let transaction_id = self
+            .submit_counter(settlement.as_ref(), &channel, &dispute, private_key)
+            .await?;
+        record.disputed_nonce = channel.nonce;
+        record.counter_tx_id = Some(transaction_id.clone());
+        self.state.disputes.track(&record)?;
+
+        Ok(transaction_id)
+    }
+
+    /// Package our evidence against a channel counterparty.
+    ///
+    /// The claimed state is our latest, signed with `private_key`. Each
+    /// evidence item is an encoded [`DisputeEvidence`]: earlier signed
+    /// states we checkpointed, unsettled payments on the channel, receipts
+    /// the counterparty signed for content we bought from it, and fraud
+    /// proofs against it.
+    pub fn dispute_evidence(
+        &self,
+        peer: &PeerId,
+        private_key: &PrivateKey,
+    ) -> OpsResult<ChannelDisputePayload> {
+        let channel = self
+            .state
+            .channels
+            .get(peer)?
+            .ok_or(OpsError::ChannelNotFound)?;
+
+        let states = self
+            .state
+            .channels
+            .checkpoints(&channel.channel_id)?
+            .into_iter()
+            .filter(|checkpoint| checkpoint.nonce < channel.nonce)
+            .map(|checkpoint| {
+                DisputeEvidence::State(ChannelUpdatePayload {
+                    channel_id: checkpoint.channel_id,
+                    nonce: checkpoint.nonce,
+                    balances: ChannelBalances::new(checkpoint.my_balance, checkpoint.their_balance),
+                    payments: vec![],
+                    signature: checkpoint.signature,
+                })
+            });
+        let payments = self
+            .state
+            .channels
+            .get_pending_payments(peer)?
+            .into_iter()
+            .map(DisputeEvidence::Payment);
+        let receipts = self
+            .state
+            .library
+            .list()?
+            .into_iter()
+            .filter(|entry| entry.provider == *peer)
+            .map(|entry| DisputeEvidence::Receipt(entry.receipt));
+        let fraud_proofs = self
+            .state
+            .fraud_proofs
+            .list(Some(peer))?
+            .into_iter()
+            .map(DisputeEvidence::FraudProof);
+
+        let evidence = states
+            .chain(payments)
+            .chain(receipts)
+            .chain(fraud_proofs)
+            .map(|item| {
+                encode_payload(&item)
+                    .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))
+            })
+            .collect::<OpsResult<Vec<_>>>()?;
+
+        Ok(ChannelDisputePayload {
+            channel_id: channel.channel_id,
+            claimed_state: signed_channel_state(&channel, private_key),
+            evidence,
+        })
+    }
+
+    /// Record a dispute seen on-chain and respond to it.
+    async fn follow_dispute(
+        &self,
+        settlement: &dyn Settlement,
+        channel: &Channel,
+        dispute: &OnChainDispute,
+        tracked: Option<DisputeRecord>,
+        private_key: &PrivateKey,
+    ) -> OpsResult<()> {
+        let config = &self.config.disputes;
+        let now = current_timestamp();
+        let is_new = tracked.is_none();
+        let mut record = tracked.unwrap_or_else(|| new_record(channel, dispute));
+        record.deadline = dispute.resolvable_at;
+        record.disputed_nonce = dispute.nonce;
+
+        if is_new && !record.opened_by_us {
+            warn!(
+                peer = %channel.peer_id,
+                channel_id = %channel.channel_id,
+                disputed_nonce = dispute.nonce,
+                our_nonce = channel.nonce,
+                deadline = dispute.resolvable_at,
+                "Channel disputed by peer"
+            );
+            self.emit(OpsEvent::DisputeOpened {
+                peer: channel.peer_id,
+                channel_id: channel.channel_id,
+                disputed_nonce: dispute.nonce,
+                our_nonce: channel.nonce,
+                deadline: dispute.resolvable_at,
+            });
+        }
+
+        if config.auto_counter && channel.nonce > dispute.nonce && now < dispute.resolvable_at {
+            match self
+                .submit_counter(settlement, channel, dispute, private_key)
+                .await
+            {
+                Ok(transaction_id) => {
+                    record.disputed_nonce = channel.nonce;
+                    record.counter_tx_id = Some(transaction_id);
+                }
+                Err(e) => {
+                    warn!(channel_id = %channel.channel_id, error = %e, "Counter-dispute failed");
+                }
+            }
+        }
+
+        let near = dispute.resolvable_at.saturating_sub(now) <= config.warn_before_ms;
+        if !record.opened_by_us && record.warned_at.is_none() && near && now < dispute.resolvable_at
+        {
+            let countered = record.counter_tx_id.is_some();
+            warn!(
+                peer = %channel.peer_id,
+                channel_id = %channel.channel_id,
+                deadline = dispute.resolvable_at,
+                countered,
+                "Dispute deadline approaching"
+            );
+            self.emit(OpsEvent::DisputeDeadlineApproaching {
+                peer: channel.peer_id,
+                channel_id: channel.channel_id,
+                deadline: dispute.resolvable_at,
+                countered,
+            });
+            record.warned_at = Some(now);
+        }
+
+        self.state.disputes.track(&record)?;
+        Ok(())
+    }
+
+    /// Submit our latest state against a dispute holding an older one.
+    async fn submit_counter(
+        &self,
+        settlement: &dyn Settlement,
+        channel: &Channel,
+        dispute: &OnChainDispute,
+        private_key: &PrivateKey,
+    ) -> OpsResult<String> {
+        if current_timestamp() >= dispute.resolvable_at {
+            return Err(OpsError::invalid_operation("dispute period has ended"));
+        }
+        if channel.nonce <= dispute.nonce {
+            return Err(OpsError::invalid_operation(format!(
+                "disputed state (nonce {}) is not older than ours (nonce {})",
+                dispute.nonce, channel.nonce
+            )));
+        }
+
+        let state = signed_channel_state(channel, private_key);
+        let transaction_id = settlement
+            .counter_dispute(&dispute.channel_id, &state)
+            .await
+            .map_err(|e| OpsError::invalid_operation(format!("counter-dispute failed: {}", e)))?
+            .to_string();
+
+        info!(
+            channel_id = %channel.channel_id,
+            tx_id = %transaction_id,
+            disputed_nonce = dispute.nonce,
+            nonce = channel.nonce,
+            "Dispute countered on-chain"
+        );
+        self.emit(OpsEvent::DisputeCountered {
+            peer: channel.peer_id,
+            channel_id: channel.channel_id,
+            nonce: channel.nonce,
+            transaction_id: transaction_id.clone(),
+        });
+
+        Ok(transaction_id)
+    }
+}
+
+/// Our latest state of a channel, signed for submission on-chain.
+pub(crate) fn signed_channel_state(
+    channel: &Channel,
+    private_key: &PrivateKey,
+) -> ChannelUpdatePayload {
+    let signature = sign_channel_close(
+        private_key,
+        &channel.channel_id,
+        channel.nonce,
+        channel.my_balance,
+        channel.their_balance,
+    );
+    ChannelUpdatePayload {
+        channel_id: channel.channel_id,
+        nonce: channel.nonce,
+        balances: ChannelBalances::new(channel.my_balance, channel.their_balance),
+        payments: vec![],
+        signature,
+    }
+}
+
+/// A record of a dispute first seen on-chain.
+fn new_record(channel: &Channel, dispute: &OnChainDispute) -> DisputeRecord {
+    DisputeRecord {
+        channel_id: channel.channel_id,
+        peer: channel.peer_id,
+        opened_by_us: channel.pending_dispute.is_some(),
+        started_at: dispute.dispute_start,
+        deadline: dispute.resolvable_at,
+        disputed_nonce: dispute.nonce,
+        counter_tx_id: None,
+        warned_at: None,
+        closed_at: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DisputeConfig, OpsConfig};
+    use crate::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key, Signature};
+    use nodalync_store::{ChannelCheckpoint, NodeState, NodeStateConfig};
+    use nodalync_test_utils::MockSettlement;
+    use nodalync_types::PendingDispute;
+    use nodalync_wire::decode_payload;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn create_ops(
+        disputes: DisputeConfig,
+        settlement: Arc<MockSettlement>,
+    ) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (_, public_key) = generate_identity();
+        let ops = DefaultNodeOperations::with_config_and_settlement(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default().with_disputes(disputes),
+            settlement,
+        );
+        (ops, temp_dir)
+    }
+
+    /// Open a channel both locally and on-chain, with our state at `nonce`.
+    async fn open_channel(
+        ops: &mut DefaultNodeOperations,
+        settlement: &MockSettlement,
+        nonce: u64,
+    ) -> Channel {
+        let (_, public_key) = generate_identity();
+        let peer = peer_id_from_public_key(&public_key);
+        let channel_id = content_hash(&peer.0);
+        ops.accept_payment_channel(&channel_id, &peer, 500, 500)
+            .unwrap();
+        settlement
+            .open_channel(&ChannelId::new(channel_id), &peer, 1000)
+            .await
+            .unwrap();
+
+        let mut channel = ops.state.channels.get(&peer).unwrap().unwrap();
+        channel.nonce = nonce;
+        channel.my_balance = 600;
+        channel.their_balance = 400;
+        ops.state.channels.update(&peer, &channel).unwrap();
+        channel
+    }
+
+    /// The counterparty disputes with its state at `nonce`.
+    async fn peer_disputes(settlement: &MockSettlement, channel: &Channel, nonce: u64) {
+        let state = ChannelUpdatePayload {
+            channel_id: channel.channel_id,
+            nonce,
+            balances: ChannelBalances::new(500, 500),
+            payments: vec![],
+            signature: Signature([0xABu8; 64]),
+        };
+        settlement
+            .dispute_channel(&ChannelId::new(channel.channel_id), &state)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_check_disputes_disabled_or_without_settlement() {
+        let settlement = Arc::new(MockSettlement::new().with_balance(10_000));
+        let (ops, _temp) = create_ops(DisputeConfig::default().with_enabled(false), settlement);
+        assert_eq!(
+            ops.check_disputes(&generate_identity().0).await.unwrap(),
+            None
+        );
+
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (_, public_key) = generate_identity();
+        let ops = DefaultNodeOperations::with_defaults(state, peer_id_from_public_key(&public_key));
+        assert_eq!(
+            ops.check_disputes(&generate_identity().0).await.unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_disputes_counters_stale_state() {
+        let settlement = Arc::new(MockSettlement::new().with_balance(10_000));
+        let config =
+            DisputeConfig::default().with_warn_before_ms(PendingDispute::DISPUTE_PERIOD_MS);
+        let (mut ops, _temp) = create_ops(config, settlement.clone());
+        let (private_key, _) = generate_identity();
+        let channel = open_channel(&mut ops, &settlement, 5).await;
+
+        // Nothing disputed yet
+        assert_eq!(
+            ops.check_disputes(&private_key).await.unwrap(),
+            Some(vec![])
+        );
+
+        peer_disputes(&settlement, &channel, 2).await;
+        let mut events = ops.subscribe_events();
+        let open = ops.check_disputes(&private_key).await.unwrap().unwrap();
+        assert_eq!(open.len(), 1);
+        let record = &open[0];
+        assert_eq!(record.peer, channel.peer_id);
+        assert!(!record.opened_by_us);
+        assert_eq!(record.disputed_nonce, 5);
+        assert!(record.counter_tx_id.is_some());
+        assert!(record.warned_at.is_some());
+
+        let on_chain = settlement
+            .get_dispute(&ChannelId::new(channel.channel_id))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(on_chain.nonce, 5);
+        assert_eq!((on_chain.balance1, on_chain.balance2), (600, 400));
+        assert_eq!(record.deadline, on_chain.resolvable_at);
+
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            OpsEvent::DisputeOpened {
+                disputed_nonce: 2,
+                our_nonce: 5,
+                ..
+            }
+        ));
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            OpsEvent::DisputeCountered { nonce: 5, .. }
+        ));
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            OpsEvent::DisputeDeadlineApproaching {
+                countered: true,
+                ..
+            }
+        ));
+
+        // Already countered and warned: nothing more to report
+        ops.check_disputes(&private_key).await.unwrap();
+        assert!(events.try_recv().is_err());
+        assert!(ops
+            .counter_dispute(&channel.peer_id, &private_key)
+            .await
+            .is_err());
+
+        // Resolved on-chain: the dispute is closed
+        settlement
+            .resolve_dispute(&ChannelId::new(channel.channel_id))
+            .await
+            .unwrap();
+        assert_eq!(
+            ops.check_disputes(&private_key).await.unwrap(),
+            Some(vec![])
+        );
+        let record = ops
+            .state
+            .disputes
+            .get(&channel.channel_id)
+            .unwrap()
+            .unwrap();
+        assert!(record.closed_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_check_disputes_without_auto_counter() {
+        let settlement = Arc::new(MockSettlement::new().with_balance(10_000));
+        let config = DisputeConfig::default().with_auto_counter(false);
+        let (mut ops, _temp) = create_ops(config, settlement.clone());
+        let (private_key, _) = generate_identity();
+        let channel = open_channel(&mut ops, &settlement, 5).await;
+        peer_disputes(&settlement, &channel, 2).await;
+
+        // Far from the deadline: recorded but neither countered nor warned
+        let open = ops.check_disputes(&private_key).await.unwrap().unwrap();
+        assert_eq!(open[0].disputed_nonce, 2);
+        assert_eq!(open[0].counter_tx_id, None);
+        assert_eq!(open[0].warned_at, None);
+
+        let tx_id = ops
+            .counter_dispute(&channel.peer_id, &private_key)
+            .await
+            .unwrap();
+        let record = ops
+            .state
+            .disputes
+            .get(&channel.channel_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.counter_tx_id, Some(tx_id));
+        assert_eq!(record.disputed_nonce, 5);
+    }
+
+    #[tokio::test]
+    async fn test_dispute_evidence() {
+        let settlement = Arc::new(MockSettlement::new().with_balance(10_000));
+        let (mut ops, _temp) = create_ops(DisputeConfig::default(), settlement.clone());
+        let (private_key, _) = generate_identity();
+        let channel = open_channel(&mut ops, &settlement, 5).await;
+        for nonce in [3, 5] {
+            ops.state
+                .channels
+                .checkpoint(&ChannelCheckpoint::new(
+                    channel.channel_id,
+                    channel.peer_id,
+                    nonce,
+                    550,
+                    450,
+                    Signature([0xCDu8; 64]),
+                    current_timestamp(),
+                ))
+                .unwrap();
+        }
+
+        let payload = ops
+            .dispute_evidence(&channel.peer_id, &private_key)
+            .unwrap();
+        assert_eq!(payload.channel_id, channel.channel_id);
+        assert_eq!(payload.claimed_state.nonce, 5);
+        assert_eq!(
+            payload.claimed_state.balances,
+            ChannelBalances::new(600, 400)
+        );
+
+        // Only the checkpoint older than our latest state is evidence
+        assert_eq!(payload.evidence.len(), 1);
+        let item: DisputeEvidence = decode_payload(&payload.evidence[0]).unwrap();
+        assert!(matches!(item, DisputeEvidence::State(state) if state.nonce == 3));
+
+        let stranger = peer_id_from_public_key(&generate_identity().1);
+        assert!(matches!(
+            ops.dispute_evidence(&stranger, &private_key),
+            Err(OpsError::ChannelNotFound)
+        ));
+    }
+}
@@ -0,0 +1,218 @@
+//! End-to-end encryption of restricted content.
+//!
+//! Content that isn't openly shared (see [`Manifest::requires_encryption`])
+//! is never sent in plaintext. The owner encrypts it under a per-content
+//! key kept in the content key store, and wraps that key to the public key
+//! the requester sent with its query. The requester unwraps the key with
+//! its private key and decrypts the content before checking its hash, so
+//! the usual hash verification covers the plaintext.
+
+use nodalync_crypto::{
+    decrypt_content, encrypt_content, unwrap_content_key, wrap_content_key, PublicKey, WrappedKey,
+};
+use nodalync_store::ContentKeyStore;
+use nodalync_types::Manifest;
+use nodalync_valid::Validator;
+use nodalync_wire::{ContentBytes, QueryResponsePayload};
+use tracing::warn;
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Our public key, sent with queries so restricted content can be
+    /// encrypted to us. `None` without a private key.
+    pub(crate) fn recipient_key(&self) -> Option<PublicKey> {
+        self.private_key().map(|key| key.public_key())
+    }
+
+    /// Prepare content for delivery to the holder of `recipient`.
+    ///
+    /// Openly shared content is returned unchanged. Anything else is
+    /// encrypted under its content key, created on first use, and the key
+    /// is wrapped to `recipient`.
+    pub(crate) fn seal_content(
+        &self,
+        manifest: &Manifest,
+        content: ContentBytes,
+        recipient: Option<&PublicKey>,
+    ) -> OpsResult<(ContentBytes, Option<WrappedKey>)> {
+        if !manifest.requires_encryption() {
+            return Ok((content, None));
+        }
+        let recipient = recipient.ok_or(OpsError::RecipientKeyRequired)?;
+
+        let key = self
+            .state
+            .content_keys
+            .get_or_create(&manifest.hash, current_timestamp())?;
+        let wrapped = wrap_content_key(&key, recipient).map_err(|_| OpsError::AccessDenied)?;
+        Ok((encrypt_content(&key, &content).into(), Some(wrapped)))
+    }
+
+    /// Decrypt a query response in place.
+    ///
+    /// Fails if the main content can't be decrypted. Bundle items that
+    /// can't be decrypted are left as they are, and are then skipped when
+    /// the bundle is cached because they don't match their hash.
+    pub(crate) fn open_response(&self, response: &mut QueryResponsePayload) -> OpsResult<()> {
+        if let Some(wrapped) = response.content_key.take() {
+            response.content = self.open_content(&wrapped, &response.content)?.into();
+        }
+
+        for item in &mut response.bundle {
+            if let Some(wrapped) = item.content_key.take() {
+                match self.open_content(&wrapped, &item.content) {
+                    Ok(content) => item.content = content,
+                    Err(e) => warn!(hash = %item.hash, error = %e, "Failed to decrypt bundle item"),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Unwrap a content key with our private key and decrypt `data`.
+    fn open_content(&self, wrapped: &WrappedKey, data: &[u8]) -> OpsResult<Vec<u8>> {
+        let private_key = self.private_key().ok_or(OpsError::PrivateKeyRequired)?;
+        let key =
+            unwrap_content_key(wrapped, private_key).map_err(|_| OpsError::DecryptionFailed)?;
+        decrypt_content(&key, data).map_err(|_| OpsError::DecryptionFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OpsConfig;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_types::{AccessControl, Metadata, Visibility};
+    use nodalync_wire::QueryRequestPayload;
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops = DefaultNodeOperations::with_config(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default(),
+        );
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    fn request(
+        hash: nodalync_crypto::Hash,
+        recipient_key: Option<PublicKey>,
+    ) -> QueryRequestPayload {
+        QueryRequestPayload {
+            hash,
+            query: None,
+            payment: None,
+            version_spec: None,
+            payment_nonce: 0,
+            capability: None,
+            recipient_key,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restricted_content_delivered_encrypted() {
+        let (owner, _owner_dir) = create_test_ops();
+        let (reader, _reader_dir) = create_test_ops();
+        let content = b"Allowlisted research notes";
+
+        let hash = owner
+            .create_content(content, Metadata::new("Notes", content.len() as u64))
+            .unwrap();
+        owner
+            .publish_content(&hash, Visibility::Shared, 0)
+            .await
+            .unwrap();
+        owner
+            .set_content_access(&hash, AccessControl::with_allowlist(vec![reader.peer_id()]))
+            .unwrap();
+
+        // Without a recipient key there is nothing to encrypt to
+        let result = owner
+            .handle_query_request(&reader.peer_id(), &request(hash, None))
+            .await;
+        assert!(matches!(result, Err(OpsError::RecipientKeyRequired)));
+
+        let mut response = owner
+            .handle_query_request(&reader.peer_id(), &request(hash, reader.recipient_key()))
+            .await
+            .unwrap();
+        assert!(response.content_key.is_some());
+        assert_ne!(&*response.content, content);
+        assert_ne!(content_hash(&response.content), hash);
+
+        // Another peer can't open it
+        let (mut outsider, _outsider_dir) = create_test_ops();
+        let mut intercepted = response.clone();
+        assert!(matches!(
+            outsider.open_response(&mut intercepted),
+            Err(OpsError::DecryptionFailed)
+        ));
+        outsider.clear_private_key();
+        assert!(matches!(
+            outsider.open_response(&mut response.clone()),
+            Err(OpsError::PrivateKeyRequired)
+        ));
+
+        // The recipient decrypts to content matching the hash
+        reader.open_response(&mut response).unwrap();
+        assert!(response.content_key.is_none());
+        assert_eq!(&*response.content, content);
+        assert_eq!(content_hash(&response.content), hash);
+
+        // The same key is used for every delivery
+        let key = owner.state.content_keys.get(&hash).unwrap().unwrap();
+        let again = owner
+            .handle_query_request(&reader.peer_id(), &request(hash, reader.recipient_key()))
+            .await
+            .unwrap();
+        let unwrapped =
+            unwrap_content_key(&again.content_key.unwrap(), reader.private_key().unwrap()).unwrap();
+        assert_eq!(unwrapped, key);
+    }
+
+    #[tokio::test]
+    async fn test_open_content_delivered_in_plaintext() {
+        let (owner, _owner_dir) = create_test_ops();
+        let (reader, _reader_dir) = create_test_ops();
+        let content = b"Public notes";
+
+        let hash = owner
+            .create_content(content, Metadata::new("Notes", content.len() as u64))
+            .unwrap();
+        owner
+            .publish_content(&hash, Visibility::Shared, 0)
+            .await
+            .unwrap();
+
+        for key in [None, reader.recipient_key()] {
+            let response = owner
+                .handle_query_request(&reader.peer_id(), &request(hash, key))
+                .await
+                .unwrap();
+            assert!(response.content_key.is_none());
+            assert_eq!(&*response.content, content);
+        }
+        assert!(owner.state.content_keys.get(&hash).unwrap().is_none());
+    }
+}
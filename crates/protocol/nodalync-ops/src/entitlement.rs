@@ -0,0 +1,356 @@
+//! Re-downloading content bought earlier.
+//!
+//! Bought content lives in the cache, which evicts it to make room. Rather
+//! than paying again, a requester presents the provider's receipt from the
+//! paid query in the request's `entitlement` field:
+//!
+//! - Providers record every paid whole-content query they serve, keyed by
+//!   the receipt's payment ID
+//! - A query with no payment and an entitlement is served free if the
+//!   receipt is the provider's, is for the requested content version, is
+//!   within the [`EntitlementConfig`](crate::config::EntitlementConfig)
+//!   window, and was issued to the requester; the usual access checks still
+//!   apply
+//! - [`redownload`](NodeOperations::redownload) fetches a library entry
+//!   again from the provider it was bought from
+//!
+//! Sections, metered units and the items of a bundle are not re-served.
+
+use nodalync_crypto::{Hash, PeerId, Timestamp};
+use nodalync_store::{CacheStore, CachedContent, Entitlement, EntitlementStore, LibraryStore};
+use nodalync_types::Manifest;
+use nodalync_valid::{validate_entitlement, ValidationError, Validator};
+use nodalync_wire::{PaymentReceipt, QueryRequestPayload, QueryResponsePayload};
+use tracing::{debug, warn};
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::helpers::verify_content_hash;
+use crate::node_ops::{current_timestamp, NodeOperations};
+use crate::ops::QueryResponse;
+use crate::trace::request_id_or_new;
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Fetch bought content again from the provider it was bought from,
+    /// presenting the purchase receipt instead of paying.
+    ///
+    /// Fails with `NotFound` if the content isn't in the library. The
+    /// content is cached again with the purchase receipt; the returned
+    /// receipt is the provider's free one for the re-download.
+    pub async fn redownload(&self, hash: &Hash) -> OpsResult<QueryResponse> {
+        let entry = self
+            .state
+            .library
+            .get(hash)?
+            .ok_or(OpsError::NotFound(*hash))?;
+        let network = self.network().cloned().ok_or_else(|| {
+            OpsError::invalid_operation("network required to re-download content")
+        })?;
+        let libp2p_peer = network
+            .libp2p_peer_id(&entry.provider)
+            .ok_or(OpsError::PeerIdNotFound)?;
+
+        let request = QueryRequestPayload {
+            hash: *hash,
+            query: None,
+            payment: None,
+            version_spec: None,
+            payment_nonce: 0,
+            capability: None,
+            recipient_key: self.recipient_key(),
+            challenge_response: None,
+            request_id: Some(request_id_or_new()),
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: Some(entry.receipt.clone()),
+        };
+        let mut response = self
+            .send_query_answering_challenge(&network, libp2p_peer, request)
+            .await?;
+
+        self.open_response(&mut response)?;
+        if !verify_content_hash(&response.content, hash) {
+            self.handle_bad_content(&entry.provider, hash, &response)
+                .await;
+            return Err(OpsError::ContentHashMismatch);
+        }
+        self.check_remote_metadata(&mut response.manifest);
+
+        self.state.cache.cache(CachedContent::new(
+            *hash,
+            response.content.to_vec(),
+            response.manifest.owner,
+            current_timestamp(),
+            entry.receipt,
+        ))?;
+        self.store_remote_manifest(&response.manifest)?;
+        debug!(hash = %hash, provider = %entry.provider, "Re-downloaded bought content");
+
+        Ok(QueryResponse {
+            content: response.content.into_vec(),
+            manifest: response.manifest,
+            receipt: response.payment_receipt,
+            bundle: Vec::new(),
+        })
+    }
+
+    /// Serve a query without payment that presents an entitlement.
+    ///
+    /// Access has already been checked. Refused with `PaymentRequired`
+    /// when re-downloads are disabled or for a section or metered unit.
+    pub(crate) fn handle_entitled_query(
+        &self,
+        requester: &PeerId,
+        request: &QueryRequestPayload,
+        receipt: &PaymentReceipt,
+        manifest: Manifest,
+        timestamp: Timestamp,
+    ) -> OpsResult<QueryResponsePayload> {
+        let config = &self.config.entitlements;
+        if !config.enabled || request.section.is_some() || request.unit.is_some() {
+            return Err(OpsError::payment_required(
+                "re-downloads are not served for this query",
+            ));
+        }
+
+        let provider_key = self
+            .private_key()
+            .ok_or(OpsError::PrivateKeyRequired)?
+            .public_key();
+        validate_entitlement(
+            receipt,
+            &request.hash,
+            requester,
+            &provider_key,
+            timestamp,
+            config.window_ms,
+        )?;
+
+        // The payment must also be one we recorded for this requester
+        let granted = self.state.entitlements.get(&receipt.payment_id)?;
+        if !granted.is_some_and(|g| g.peer == *requester && g.content_hash == request.hash) {
+            return Err(ValidationError::InvalidEntitlement {
+                reason: "no paid query by this peer".to_string(),
+            }
+            .into());
+        }
+        self.state
+            .entitlements
+            .record_redemption(&receipt.payment_id, timestamp)?;
+
+        debug!(hash = %request.hash, requester = %requester, "Serving re-download");
+        self.handle_free_query(requester, request, manifest, timestamp)
+    }
+
+    /// Record a paid query we served, so its receipt entitles the requester
+    /// to re-download the content.
+    pub(crate) fn grant_entitlement(
+        &self,
+        requester: &PeerId,
+        request: &QueryRequestPayload,
+        receipt: &PaymentReceipt,
+    ) {
+        if receipt.amount == 0 || request.section.is_some() || request.unit.is_some() {
+            return;
+        }
+
+        let entitlement = Entitlement {
+            payment_id: receipt.payment_id,
+            content_hash: request.hash,
+            peer: *requester,
+            amount: receipt.amount,
+            granted_at: receipt.timestamp,
+            redemptions: 0,
+            last_redeemed_at: None,
+        };
+        let cutoff = receipt
+            .timestamp
+            .saturating_sub(self.config.entitlements.window_ms);
+        let result = self
+            .state
+            .entitlements
+            .grant(&entitlement)
+            .and_then(|()| self.state.entitlements.prune(cutoff));
+        if let Err(e) = result {
+            warn!(hash = %request.hash, error = %e, "Failed to record entitlement");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key, Signature};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_test_utils::MockSettlement;
+    use nodalync_types::{Metadata, Payment, Visibility};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    const PRICE: u64 = 100;
+
+    fn create_provider() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops = DefaultNodeOperations::with_defaults_and_settlement(
+            state,
+            peer_id_from_public_key(&public_key),
+            Arc::new(MockSettlement::new()),
+        );
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    fn request(hash: Hash) -> QueryRequestPayload {
+        QueryRequestPayload {
+            hash,
+            query: None,
+            payment: None,
+            version_spec: None,
+            payment_nonce: 0,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        }
+    }
+
+    /// Buy `hash` from `ops` as `requester`, returning the receipt.
+    async fn buy(ops: &DefaultNodeOperations, hash: Hash, requester: &PeerId) -> PaymentReceipt {
+        let channel_id = content_hash(b"entitlement-channel");
+        ops.accept_payment_channel(&channel_id, requester, 5_000, 1_000)
+            .unwrap();
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        let paid = QueryRequestPayload {
+            payment: Some(Payment::new(
+                content_hash(b"payment"),
+                channel_id,
+                PRICE,
+                manifest.owner,
+                hash,
+                manifest.provenance.root_l0l1.clone(),
+                current_timestamp(),
+                Signature::from_bytes([0u8; 64]),
+            )),
+            payment_nonce: 1,
+            ..request(hash)
+        };
+        ops.handle_query_request(requester, &paid)
+            .await
+            .unwrap()
+            .payment_receipt
+    }
+
+    #[tokio::test]
+    async fn test_paid_query_can_be_redownloaded() {
+        let (ops, _dir) = create_provider();
+        let content = b"Bought once, read twice";
+        let hash = ops
+            .create_content(content, Metadata::new("Report", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, PRICE)
+            .await
+            .unwrap();
+
+        let buyer = test_peer_id();
+        let receipt = buy(&ops, hash, &buyer).await;
+        let granted = ops
+            .state
+            .entitlements
+            .get(&receipt.payment_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(granted.peer, buyer);
+        assert_eq!(granted.amount, PRICE);
+
+        // The receipt gets the content again without paying
+        let redownload = QueryRequestPayload {
+            entitlement: Some(receipt.clone()),
+            ..request(hash)
+        };
+        let response = ops.handle_query_request(&buyer, &redownload).await.unwrap();
+        assert_eq!(response.content, content.to_vec());
+        assert_eq!(response.payment_receipt.amount, 0);
+        let granted = ops
+            .state
+            .entitlements
+            .get(&receipt.payment_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(granted.redemptions, 1);
+
+        // But not for another peer presenting it
+        assert!(matches!(
+            ops.handle_query_request(&test_peer_id(), &redownload).await,
+            Err(OpsError::Validation(
+                ValidationError::InvalidEntitlement { .. }
+            ))
+        ));
+
+        // Nor with a raised amount
+        let raised = QueryRequestPayload {
+            entitlement: Some(PaymentReceipt {
+                amount: PRICE * 10,
+                ..receipt.clone()
+            }),
+            ..request(hash)
+        };
+        assert!(matches!(
+            ops.handle_query_request(&buyer, &raised).await,
+            Err(OpsError::Validation(
+                ValidationError::InvalidEntitlementSignature
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_redownloads_disabled() {
+        let (mut ops, _dir) = create_provider();
+        let content = b"Bought once, read once";
+        let hash = ops
+            .create_content(content, Metadata::new("Report", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, PRICE)
+            .await
+            .unwrap();
+
+        let buyer = test_peer_id();
+        let receipt = buy(&ops, hash, &buyer).await;
+        ops.config.entitlements.enabled = false;
+
+        let redownload = QueryRequestPayload {
+            entitlement: Some(receipt),
+            ..request(hash)
+        };
+        assert!(matches!(
+            ops.handle_query_request(&buyer, &redownload).await,
+            Err(OpsError::PaymentRequired(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_redownload_needs_library_entry() {
+        let (ops, _dir) = create_provider();
+        let hash = content_hash(b"never bought");
+        assert!(matches!(
+            ops.redownload(&hash).await,
+            Err(OpsError::NotFound(_))
+        ));
+    }
+}
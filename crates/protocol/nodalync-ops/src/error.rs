@@ -4,7 +4,7 @@
 //! functions in this crate.
 
 use nodalync_crypto::Hash;
-use nodalync_types::ErrorCode;
+use nodalync_types::{Amount, ErrorCode};
 use thiserror::Error;
 
 /// Result type for operations.
@@ -76,6 +76,50 @@ pub enum OpsError {
     #[error("content is not an L3")]
     NotAnL3,
 
+    /// Encrypted content could not be decrypted with the delivered key.
+    #[error("content decryption failed")]
+    DecryptionFailed,
+
+    /// Content has been withdrawn by its owner (tombstoned).
+    #[error("content withdrawn: {0}")]
+    ContentWithdrawn(Hash),
+
+    /// Remote content was rejected by the trust policy.
+    #[error("content {hash} rejected by trust policy: {reason}")]
+    TrustRejected {
+        /// Rejected content
+        hash: Hash,
+        /// Failed checks
+        reason: String,
+    },
+
+    /// No provider returned a chunk that matched the chunk tree.
+    #[error("chunk {index} of {hash} unavailable from any provider")]
+    ChunkUnavailable {
+        /// Content being downloaded
+        hash: Hash,
+        /// Chunk that could not be fetched
+        index: u32,
+    },
+
+    /// The manifest has no section at the requested index.
+    #[error("{hash} has no section {index}")]
+    SectionNotFound {
+        /// Content queried
+        hash: Hash,
+        /// Section that was asked for
+        index: u32,
+    },
+
+    /// The content isn't metered, or has no unit at the requested index.
+    #[error("{hash} has no metered unit {index}")]
+    UnitNotFound {
+        /// Content queried
+        hash: Hash,
+        /// Unit that was asked for
+        index: u32,
+    },
+
     // =========================================================================
     // Access Errors
     // =========================================================================
@@ -83,6 +127,36 @@ pub enum OpsError {
     #[error("access denied")]
     AccessDenied,
 
+    /// Group not found locally.
+    #[error("group not found: {0}")]
+    GroupNotFound(Hash),
+
+    /// Restricted content requested without a key to encrypt it to.
+    #[error("recipient key required for restricted content")]
+    RecipientKeyRequired,
+
+    /// No tenant has this API key.
+    #[error("invalid tenant API key")]
+    TenantUnauthorized,
+
+    /// A tenant's budget can't cover a query.
+    #[error("tenant {tenant} budget exceeded: {remaining} remaining, {required} required")]
+    TenantBudgetExceeded {
+        /// Tenant charged.
+        tenant: String,
+        /// Budget left.
+        remaining: Amount,
+        /// Amount the query may cost.
+        required: Amount,
+    },
+
+    /// Too many queries are being served; the requester should retry.
+    #[error("busy, retry after {retry_after_ms} ms")]
+    Busy {
+        /// Suggested delay before retrying, in milliseconds
+        retry_after_ms: u64,
+    },
+
     // =========================================================================
     // Payment Errors
     // =========================================================================
@@ -113,6 +187,10 @@ pub enum OpsError {
         libp2p_peer_id: Option<String>,
     },
 
+    /// The requester must answer this challenge before the query is served.
+    #[error("query challenge required")]
+    ChallengeRequired(nodalync_wire::QueryChallenge),
+
     /// Insufficient balance in channel.
     #[error("insufficient channel balance")]
     InsufficientChannelBalance,
@@ -121,6 +199,14 @@ pub enum OpsError {
     #[error("private key required for paid queries")]
     PrivateKeyRequired,
 
+    /// Invoice not found.
+    #[error("invoice not found: {0}")]
+    InvoiceNotFound(Hash),
+
+    /// The other party rejected an invoice or invoice payment.
+    #[error("invoice rejected: {0}")]
+    InvoiceRejected(String),
+
     // =========================================================================
     // Channel Errors
     // =========================================================================
@@ -145,6 +231,10 @@ pub enum OpsError {
         minimum: u64,
     },
 
+    /// Auto-opening a channel was refused by the auto-open policy.
+    #[error("channel auto-open denied: {0}")]
+    AutoOpenDenied(String),
+
     // =========================================================================
     // Settlement Errors
     // =========================================================================
@@ -167,6 +257,14 @@ pub enum OpsError {
     #[error("manifest not found: {0}")]
     ManifestNotFound(Hash),
 
+    /// The publisher refused a usage report.
+    #[error("usage report rejected: {0}")]
+    UsageReportRejected(String),
+
+    /// The operations configuration is inconsistent or malformed.
+    #[error("invalid config: {0}")]
+    InvalidConfig(String),
+
     // =========================================================================
     // Network Errors
     // =========================================================================
@@ -200,6 +298,11 @@ impl OpsError {
         OpsError::InvalidOperation(msg.into())
     }
 
+    /// Create an invalid configuration error.
+    pub fn invalid_config(msg: impl Into<String>) -> Self {
+        OpsError::InvalidConfig(msg.into())
+    }
+
     /// Create a payment required error.
     pub fn payment_required(msg: impl Into<String>) -> Self {
         OpsError::PaymentRequired(msg.into())
@@ -215,9 +318,20 @@ impl OpsError {
             Self::SourceNotQueried(_) => ErrorCode::NotFound,
             Self::ContentHashMismatch => ErrorCode::InvalidHash,
             Self::NotAnL3 => ErrorCode::InvalidManifest,
+            Self::DecryptionFailed => ErrorCode::InvalidHash,
+            Self::ContentWithdrawn(_) => ErrorCode::NotFound,
+            Self::TrustRejected { .. } => ErrorCode::AccessDenied,
+            Self::ChunkUnavailable { .. } => ErrorCode::NotFound,
+            Self::SectionNotFound { .. } => ErrorCode::NotFound,
+            Self::UnitNotFound { .. } => ErrorCode::NotFound,
 
             // Access errors
             Self::AccessDenied => ErrorCode::AccessDenied,
+            Self::GroupNotFound(_) => ErrorCode::NotFound,
+            Self::RecipientKeyRequired => ErrorCode::AccessDenied,
+            Self::TenantUnauthorized => ErrorCode::AccessDenied,
+            Self::TenantBudgetExceeded { .. } => ErrorCode::InsufficientBalance,
+            Self::Busy { .. } => ErrorCode::RateLimited,
 
             // Payment errors
             Self::PaymentRequired(_) => ErrorCode::PaymentRequired,
@@ -225,14 +339,18 @@ impl OpsError {
             Self::PaymentValidationFailed(_) => ErrorCode::PaymentInvalid,
             Self::ChannelRequired => ErrorCode::ChannelNotFound,
             Self::ChannelRequiredWithPeerInfo { .. } => ErrorCode::ChannelNotFound,
+            Self::ChallengeRequired(_) => ErrorCode::ChallengeRequired,
             Self::InsufficientChannelBalance => ErrorCode::InsufficientBalance,
             Self::PrivateKeyRequired => ErrorCode::PaymentInvalid,
+            Self::InvoiceNotFound(_) => ErrorCode::NotFound,
+            Self::InvoiceRejected(_) => ErrorCode::PaymentInvalid,
 
             // Channel errors
             Self::ChannelNotFound => ErrorCode::ChannelNotFound,
             Self::ChannelAlreadyExists => ErrorCode::ChannelNotFound, // Closest match
             Self::ChannelNotOpen => ErrorCode::ChannelClosed,
             Self::ChannelDepositTooLow { .. } => ErrorCode::PaymentInvalid,
+            Self::AutoOpenDenied(_) => ErrorCode::ChannelNotFound, // No channel was opened
 
             // Settlement errors
             Self::SettlementFailed(_) => ErrorCode::InternalError,
@@ -240,17 +358,60 @@ impl OpsError {
 
             // Operation errors
             Self::InvalidOperation(_) => ErrorCode::InvalidManifest,
+            Self::UsageReportRejected(_) => ErrorCode::AccessDenied,
+            Self::InvalidConfig(_) => ErrorCode::InternalError,
 
             // Network errors
-            Self::Network(_) => ErrorCode::ConnectionFailed,
             Self::PeerIdNotFound => ErrorCode::PeerNotFound,
 
             // Wrapped errors - delegate to inner type
+            Self::Network(e) => e.error_code(),
             Self::Validation(e) => e.error_code(),
-            Self::Store(_) => ErrorCode::InternalError,
-            Self::Econ(_) => ErrorCode::InternalError,
+            Self::Store(e) => e.error_code(),
+            Self::Econ(e) => e.error_code(),
         }
     }
+
+    /// How long to wait before retrying, in milliseconds, if the error is
+    /// temporary.
+    ///
+    /// A busy provider names its delay; embargoed content can be retried
+    /// once it is published, and a used-up quota once it resets; a
+    /// provider's own retry delay is passed on.
+    pub fn retry_after_ms(&self) -> Option<u64> {
+        match self {
+            Self::Busy { retry_after_ms } => Some(*retry_after_ms),
+            Self::Validation(nodalync_valid::ValidationError::Embargoed { publish_at }) => {
+                Some(publish_at.saturating_sub(crate::node_ops::current_timestamp()))
+            }
+            Self::Validation(nodalync_valid::ValidationError::QuotaExceeded {
+                resets_at, ..
+            }) => Some(resets_at.saturating_sub(crate::node_ops::current_timestamp())),
+            Self::Network(nodalync_net::NetworkError::QueryError { retry_after_ms, .. }) => {
+                *retry_after_ms
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the same request may succeed when retried.
+    pub fn is_retryable(&self) -> bool {
+        self.error_code().is_retryable() || self.retry_after_ms().is_some()
+    }
+
+    /// Whether another provider may serve what this error refused.
+    pub fn suggests_alternatives(&self) -> bool {
+        matches!(
+            self,
+            Self::NotFound(_) | Self::ManifestNotFound(_) | Self::Busy { .. }
+        )
+    }
+}
+
+impl From<&OpsError> for ErrorCode {
+    fn from(error: &OpsError) -> Self {
+        error.error_code()
+    }
 }
 
 #[cfg(test)]
@@ -287,6 +448,27 @@ mod tests {
 
         // Access errors
         assert_eq!(OpsError::AccessDenied.error_code(), ErrorCode::AccessDenied);
+        assert_eq!(
+            OpsError::GroupNotFound(hash).error_code(),
+            ErrorCode::NotFound
+        );
+        assert_eq!(
+            OpsError::RecipientKeyRequired.error_code(),
+            ErrorCode::AccessDenied
+        );
+        assert_eq!(
+            OpsError::TenantUnauthorized.error_code(),
+            ErrorCode::AccessDenied
+        );
+        assert_eq!(
+            OpsError::TenantBudgetExceeded {
+                tenant: "acme".into(),
+                remaining: 5,
+                required: 10,
+            }
+            .error_code(),
+            ErrorCode::InsufficientBalance
+        );
 
         // Payment errors
         assert_eq!(
@@ -301,6 +483,14 @@ mod tests {
             OpsError::InsufficientChannelBalance.error_code(),
             ErrorCode::InsufficientBalance
         );
+        assert_eq!(
+            OpsError::InvoiceNotFound(hash).error_code(),
+            ErrorCode::NotFound
+        );
+        assert_eq!(
+            OpsError::InvoiceRejected("expired".into()).error_code(),
+            ErrorCode::PaymentInvalid
+        );
 
         // Channel errors
         assert_eq!(
@@ -311,11 +501,81 @@ mod tests {
             OpsError::ChannelNotOpen.error_code(),
             ErrorCode::ChannelClosed
         );
+        assert_eq!(
+            OpsError::AutoOpenDenied("limit".into()).error_code(),
+            ErrorCode::ChannelNotFound
+        );
+
+        // Operation errors
+        assert_eq!(
+            OpsError::UsageReportRejected("rate limited".into()).error_code(),
+            ErrorCode::AccessDenied
+        );
+        assert_eq!(
+            OpsError::TrustRejected {
+                hash,
+                reason: "price: too high".into()
+            }
+            .error_code(),
+            ErrorCode::AccessDenied
+        );
+        assert_eq!(
+            OpsError::ChunkUnavailable { hash, index: 2 }.error_code(),
+            ErrorCode::NotFound
+        );
+        assert_eq!(
+            OpsError::SectionNotFound { hash, index: 2 }.error_code(),
+            ErrorCode::NotFound
+        );
+        assert_eq!(
+            OpsError::UnitNotFound { hash, index: 2 }.error_code(),
+            ErrorCode::NotFound
+        );
 
         // Network errors
         assert_eq!(
             OpsError::PeerIdNotFound.error_code(),
             ErrorCode::PeerNotFound
         );
+        assert_eq!(
+            OpsError::Network(nodalync_net::NetworkError::Timeout("5s".into())).error_code(),
+            ErrorCode::Timeout
+        );
+
+        // Wrapped errors keep their own codes
+        assert_eq!(
+            OpsError::Store(nodalync_store::StoreError::ManifestNotFound(hash)).error_code(),
+            ErrorCode::NotFound
+        );
+        assert_eq!(
+            ErrorCode::from(&OpsError::Econ(nodalync_econ::EconError::EmptyProvenance)),
+            ErrorCode::InvalidProvenance
+        );
+    }
+
+    #[test]
+    fn test_retry_hints() {
+        let hash = content_hash(b"test");
+        let busy = OpsError::Busy {
+            retry_after_ms: 250,
+        };
+        assert_eq!(busy.retry_after_ms(), Some(250));
+        assert_eq!(busy.error_code(), ErrorCode::RateLimited);
+        assert!(busy.suggests_alternatives());
+
+        let publish_at = crate::node_ops::current_timestamp() + 60_000;
+        let embargoed =
+            OpsError::Validation(nodalync_valid::ValidationError::Embargoed { publish_at });
+        let wait = embargoed.retry_after_ms().unwrap();
+        assert!(wait > 0 && wait <= 60_000);
+        assert!(!embargoed.suggests_alternatives());
+
+        assert!(busy.is_retryable());
+        assert!(embargoed.is_retryable());
+        assert!(!OpsError::AccessDenied.is_retryable());
+
+        assert!(OpsError::NotFound(hash).suggests_alternatives());
+        assert_eq!(OpsError::NotFound(hash).retry_after_ms(), None);
+        assert!(!OpsError::AccessDenied.suggests_alternatives());
     }
 }
@@ -0,0 +1,225 @@
+//! Operations event bus.
+//!
+//! Long-running operations report progress as `OpsEvent`s on a broadcast
+//! channel owned by `NodeOperations`. Anyone interested (the MCP server, the
+//! CLI, tests) calls `subscribe_events()`; events sent while nobody is
+//! subscribed are dropped. Economic and content events are also queued
+//! for delivery to webhooks (see [`crate::webhook`]).
+
+use std::path::PathBuf;
+
+use nodalync_crypto::{Hash, PeerId, Timestamp};
+use nodalync_types::Amount;
+use nodalync_valid::Validator;
+use tokio::sync::broadcast;
+
+use crate::extraction::L1Extractor;
+use crate::node_ops::NodeOperations;
+
+/// Number of events buffered per subscriber before the oldest are dropped.
+pub const EVENT_BUS_CAPACITY: usize = 256;
+
+/// How a single channel fared in a batched close.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelCloseStatus {
+    /// Closed cooperatively (on-chain or off-chain).
+    Closed,
+    /// Peer did not answer in time; the channel keeps its pending close.
+    Unresponsive,
+    /// The close could not be started or finished.
+    Failed,
+}
+
+/// Progress and lifecycle events emitted by operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OpsEvent {
+    /// A batched close of `total` channels started.
+    ChannelCloseStarted {
+        /// Channels being closed.
+        total: usize,
+    },
+    /// One channel of a batched close finished.
+    ChannelCloseProgress {
+        /// Channel counterparty.
+        peer: PeerId,
+        /// Outcome for this channel.
+        status: ChannelCloseStatus,
+        /// Channels finished so far, including this one.
+        completed: usize,
+        /// Channels in the batch.
+        total: usize,
+    },
+    /// A batched close finished.
+    ChannelCloseFinished {
+        /// Channels closed cooperatively.
+        closed: usize,
+        /// Channels whose peer did not answer.
+        unresponsive: usize,
+        /// Channels that failed.
+        failed: usize,
+    },
+    /// A settlement batch was created (and submitted, if settlement is configured).
+    SettlementBatch {
+        /// Batch identifier.
+        batch_id: Hash,
+    },
+    /// A settlement batch was settled and the queue marked settled.
+    SettlementConfirmed {
+        /// Batch identifier.
+        batch_id: Hash,
+        /// Settlement transaction ID (`local-...` without on-chain settlement).
+        transaction_id: String,
+        /// Total amount settled.
+        amount: Amount,
+    },
+    /// A paid query we served was settled before delivery.
+    PaymentReceived {
+        /// Payment identifier.
+        payment_id: Hash,
+        /// Peer that paid.
+        payer: PeerId,
+        /// Content that was queried.
+        content_hash: Hash,
+        /// Amount paid.
+        amount: Amount,
+        /// Settlement transaction ID.
+        transaction_id: Option<String>,
+    },
+    /// A query for our content was served.
+    ContentQueried {
+        /// Content that was queried.
+        content_hash: Hash,
+        /// Peer that queried it.
+        requester: PeerId,
+        /// Amount paid (0 for free content).
+        amount: Amount,
+    },
+    /// We opened a dispute on a payment channel.
+    ChannelDisputed {
+        /// Channel counterparty.
+        peer: PeerId,
+        /// Channel identifier.
+        channel_id: Hash,
+        /// Dispute transaction ID.
+        transaction_id: String,
+    },
+    /// A counterparty opened a dispute on one of our channels.
+    DisputeOpened {
+        /// Channel counterparty.
+        peer: PeerId,
+        /// Channel identifier.
+        channel_id: Hash,
+        /// Nonce of the state the dispute was opened with.
+        disputed_nonce: u64,
+        /// Nonce of our latest state.
+        our_nonce: u64,
+        /// When the dispute period ends (ms).
+        deadline: Timestamp,
+    },
+    /// The deadline to respond to a dispute is near.
+    DisputeDeadlineApproaching {
+        /// Channel counterparty.
+        peer: PeerId,
+        /// Channel identifier.
+        channel_id: Hash,
+        /// When the dispute period ends (ms).
+        deadline: Timestamp,
+        /// Whether we have countered it.
+        countered: bool,
+    },
+    /// We countered a dispute with our latest state.
+    DisputeCountered {
+        /// Channel counterparty.
+        peer: PeerId,
+        /// Channel identifier.
+        channel_id: Hash,
+        /// Nonce of the state we submitted.
+        nonce: u64,
+        /// Counter-dispute transaction ID.
+        transaction_id: String,
+    },
+    /// The settlement balance was topped up automatically.
+    SettlementToppedUp {
+        /// Amount deposited.
+        amount: Amount,
+        /// Settlement balance after the deposit.
+        balance: Amount,
+    },
+    /// Content received from a peer was flagged by the trust policy.
+    ContentFlagged {
+        /// Flagged content.
+        hash: Hash,
+        /// Trust score (0-100).
+        score: u8,
+        /// Checks that failed.
+        reasons: Vec<String>,
+    },
+    /// A provider was proven to have served the wrong content.
+    FraudProven {
+        /// Provider the proof is against.
+        provider: PeerId,
+        /// Content that was requested.
+        content_hash: Hash,
+        /// Hash of the fraud proof.
+        proof_hash: Hash,
+    },
+    /// The local clock is further from the network's than the tolerance.
+    ClockSkewExceeded {
+        /// Milliseconds the network's clock is ahead of ours (negative when
+        /// behind).
+        offset_ms: i64,
+        /// Configured tolerance in milliseconds.
+        tolerance_ms: u64,
+    },
+    /// The settlement balance is low but the daily top-up cap was reached.
+    TopUpCapReached {
+        /// Current settlement balance.
+        balance: Amount,
+        /// Top-ups made in the last 24 hours.
+        deposited_today: Amount,
+        /// Configured daily cap.
+        daily_cap: Amount,
+    },
+    /// A standing order bought announced content.
+    StandingOrderFilled {
+        /// Order that bought it.
+        order_id: u64,
+        /// Content bought.
+        content_hash: Hash,
+        /// Amount paid.
+        amount: Amount,
+        /// Where the content was written (`None` without an inbox).
+        path: Option<PathBuf>,
+    },
+    /// Our content failed `alert_after` availability checks in a row.
+    ContentUnreachable {
+        /// Content that couldn't be fetched.
+        hash: Hash,
+        /// Peer that made the latest check.
+        checker: PeerId,
+        /// Checks failed since the last success.
+        consecutive_failures: u32,
+        /// Why the latest check failed.
+        error: Option<String>,
+    },
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Subscribe to operation events.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<OpsEvent> {
+        self.events.subscribe()
+    }
+
+    /// Send an event to all subscribers, and queue it for the webhooks
+    /// registered for it.
+    pub(crate) fn emit(&self, event: OpsEvent) {
+        self.queue_webhooks(&event);
+        // No subscribers is not an error
+        let _ = self.events.send(event);
+    }
+}
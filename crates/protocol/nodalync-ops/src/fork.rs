@@ -0,0 +1,315 @@
+//! Version forks and their resolution.
+//!
+//! Nothing stops an owner publishing two different versions with the same
+//! number in one lineage. Whenever a version is stored, whether our own
+//! update or a manifest received from a peer, the validator checks it
+//! against the versions already held; a version that forks with another
+//! is stored with `version.forked` set, and the versions it conflicts
+//! with are flagged too.
+//!
+//! [`NodeOperations::current_version`] picks the version a lineage
+//! resolves to under the configured [`ForkPolicy`]: the fork with the
+//! latest timestamp, or the one named by the owner's signed
+//! [`CanonicalVersion`] pointer. Owners sign pointers with
+//! [`NodeOperations::set_canonical_version`]; peers receive them with
+//! previews and version responses.
+
+use nodalync_crypto::{Hash, PeerId};
+use nodalync_store::ManifestStore;
+use nodalync_types::{CanonicalVersion, Manifest};
+use nodalync_valid::{
+    find_version_forks, sign_canonical_version, validate_canonical_version, Validator,
+};
+use tracing::{debug, info, warn};
+
+use crate::config::ForkPolicy;
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Versions held that fork with `manifest`.
+    pub fn version_forks(&self, manifest: &Manifest) -> OpsResult<Vec<Hash>> {
+        let known = self.state.manifests.get_versions(&manifest.version.root)?;
+        Ok(find_version_forks(manifest, &known))
+    }
+
+    /// The version a lineage currently resolves to.
+    ///
+    /// The highest numbered version held wins. If the owner forked that
+    /// version, the fork policy decides: under
+    /// [`ForkPolicy::OwnerCanonical`] the owner's canonical pointer wins
+    /// when it names one of the forks; otherwise the fork with the latest
+    /// timestamp does. Returns `None` if no version of `root` is held.
+    pub fn current_version(&self, root: &Hash) -> OpsResult<Option<Hash>> {
+        let manifests = self.state.manifests.get_versions(root)?;
+        self.resolve_current_version(root, &manifests)
+    }
+
+    /// Sign and store a canonical pointer choosing `hash` among the forks
+    /// of its lineage.
+    ///
+    /// Only the owner can choose. The pointer supersedes earlier ones and
+    /// is handed to peers with previews and version responses.
+    pub fn set_canonical_version(&self, hash: &Hash) -> OpsResult<CanonicalVersion> {
+        let manifest = self
+            .state
+            .manifests
+            .load(hash)?
+            .ok_or(OpsError::ManifestNotFound(*hash))?;
+        if manifest.owner != self.peer_id() {
+            return Err(OpsError::AccessDenied);
+        }
+        let private_key = self.private_key().ok_or(OpsError::PrivateKeyRequired)?;
+
+        let mut pointer = CanonicalVersion::new(
+            manifest.version.root,
+            *hash,
+            self.peer_id(),
+            private_key.public_key(),
+            current_timestamp(),
+        );
+        sign_canonical_version(private_key, &mut pointer);
+        self.state.manifests.set_canonical(&pointer)?;
+
+        info!(root = %pointer.root, version = %hash, "Set canonical version");
+        Ok(pointer)
+    }
+
+    /// Get the canonical pointer held for a lineage.
+    pub fn get_canonical_version(&self, root: &Hash) -> OpsResult<Option<CanonicalVersion>> {
+        Ok(self.state.manifests.get_canonical(root)?)
+    }
+
+    /// Store a canonical pointer received from a peer.
+    ///
+    /// The pointer must be signed by `lineage_owner`, the owner of the
+    /// versions it chooses between. Pointers for our own lineages, and
+    /// pointers older than the one held, are ignored. Returns whether the
+    /// pointer was stored; an invalid pointer is logged and dropped.
+    pub(crate) fn accept_canonical_version(
+        &self,
+        pointer: &CanonicalVersion,
+        lineage_owner: &PeerId,
+    ) -> bool {
+        if *lineage_owner == self.peer_id() {
+            return false;
+        }
+        if let Err(e) = validate_canonical_version(pointer, Some(lineage_owner)) {
+            warn!(root = %pointer.root, error = %e, "Rejected canonical version pointer");
+            return false;
+        }
+        match self.state.manifests.set_canonical(pointer) {
+            Ok(stored) => {
+                if stored {
+                    debug!(root = %pointer.root, version = %pointer.version, "Stored canonical version pointer");
+                }
+                stored
+            }
+            Err(e) => {
+                warn!(root = %pointer.root, error = %e, "Failed to store canonical version pointer");
+                false
+            }
+        }
+    }
+
+    /// Validate a new version against the held versions of its lineage
+    /// and flag any forks.
+    ///
+    /// Sets `forked` on `manifest` and marks the held versions it forks
+    /// with. Returns the hashes of those versions.
+    pub(crate) fn flag_version_forks(
+        &self,
+        manifest: &mut Manifest,
+        previous: Option<&Manifest>,
+    ) -> OpsResult<Vec<Hash>> {
+        let known = self.state.manifests.get_versions(&manifest.version.root)?;
+        let forks = self
+            .validator
+            .validate_version_in_lineage(manifest, previous, &known)?;
+        if forks.is_empty() {
+            return Ok(forks);
+        }
+
+        manifest.version.forked = true;
+        for mut other in known {
+            if forks.contains(&other.hash) && !other.version.forked {
+                other.version.forked = true;
+                self.state.manifests.update(&other)?;
+            }
+        }
+        warn!(
+            root = %manifest.version.root,
+            number = manifest.version.number,
+            hash = %manifest.hash,
+            forks = forks.len(),
+            "Owner published more than one version with the same number"
+        );
+        Ok(forks)
+    }
+
+    /// Store a manifest received from a peer, flagging version forks.
+    ///
+    /// Remote manifests whose version fields don't check out are still
+    /// stored (as before fork detection), just not compared.
+    pub(crate) fn store_remote_manifest(&self, manifest: &Manifest) -> OpsResult<()> {
+        let mut manifest = manifest.clone();
+        manifest.version.forked = false;
+        let previous = match manifest.version.previous {
+            Some(previous) => self.state.manifests.load(&previous)?,
+            None => None,
+        };
+        if let Err(e) = self.flag_version_forks(&mut manifest, previous.as_ref()) {
+            debug!(hash = %manifest.hash, error = %e, "Skipped fork check for remote manifest");
+        }
+        self.state.manifests.store(&manifest)?;
+        Ok(())
+    }
+
+    /// Pick the current version among the held versions of a lineage.
+    pub(crate) fn resolve_current_version(
+        &self,
+        root: &Hash,
+        manifests: &[Manifest],
+    ) -> OpsResult<Option<Hash>> {
+        let Some(top) = manifests.iter().map(|m| m.version.number).max() else {
+            return Ok(None);
+        };
+        let tips: Vec<&Manifest> = manifests
+            .iter()
+            .filter(|m| m.version.number == top)
+            .collect();
+
+        if tips.len() > 1 && self.config.fork_policy == ForkPolicy::OwnerCanonical {
+            if let Some(pointer) = self.state.manifests.get_canonical(root)? {
+                if tips.iter().any(|m| m.hash == pointer.version) {
+                    return Ok(Some(pointer.version));
+                }
+            }
+        }
+        Ok(tips
+            .into_iter()
+            .max_by_key(|m| (m.version.timestamp, m.hash.0))
+            .map(|m| m.hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OpsConfig;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_types::Metadata;
+    use tempfile::TempDir;
+
+    fn create_test_ops(config: OpsConfig) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops =
+            DefaultNodeOperations::with_config(state, peer_id_from_public_key(&public_key), config);
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    /// v1 with two competing v2s, the second one later; returns
+    /// (v1, v2a, v2b).
+    fn create_fork(ops: &DefaultNodeOperations) -> (Hash, Hash, Hash) {
+        let v1 = ops
+            .create_content(b"Version 1", Metadata::new("Doc", 9))
+            .unwrap();
+        let now = current_timestamp();
+        let v2a = ops
+            .update_content_with_timestamp(
+                &v1,
+                b"Version 2a",
+                Metadata::new("Doc", 10),
+                now + 1_000,
+            )
+            .unwrap();
+        let v2b = ops
+            .update_content_with_timestamp(
+                &v1,
+                b"Version 2b",
+                Metadata::new("Doc", 10),
+                now + 2_000,
+            )
+            .unwrap();
+        (v1, v2a, v2b)
+    }
+
+    #[test]
+    fn test_update_flags_forks() {
+        let (ops, _temp) = create_test_ops(OpsConfig::default());
+        let (v1, v2a, v2b) = create_fork(&ops);
+
+        let load = |hash: &Hash| ops.state.manifests.load(hash).unwrap().unwrap();
+        assert!(!load(&v1).version.forked);
+        assert!(load(&v2a).version.forked);
+        assert!(load(&v2b).version.forked);
+        assert_eq!(ops.version_forks(&load(&v2a)).unwrap(), vec![v2b]);
+        assert!(ops.version_forks(&load(&v1)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_current_version_latest_timestamp() {
+        let (ops, _temp) = create_test_ops(OpsConfig::default());
+        let (v1, v2a, v2b) = create_fork(&ops);
+        assert_eq!(ops.current_version(&v1).unwrap(), Some(v2b));
+
+        // A canonical pointer doesn't count under this policy
+        ops.set_canonical_version(&v2a).unwrap();
+        assert_eq!(ops.current_version(&v1).unwrap(), Some(v2b));
+    }
+
+    #[test]
+    fn test_current_version_owner_canonical() {
+        let config = OpsConfig::default().with_fork_policy(ForkPolicy::OwnerCanonical);
+        let (ops, _temp) = create_test_ops(config);
+        let (v1, v2a, v2b) = create_fork(&ops);
+
+        // No pointer: falls back to the latest timestamp
+        assert_eq!(ops.current_version(&v1).unwrap(), Some(v2b));
+
+        let pointer = ops.set_canonical_version(&v2a).unwrap();
+        assert_eq!(pointer.root, v1);
+        assert_eq!(ops.get_canonical_version(&v1).unwrap(), Some(pointer));
+        assert_eq!(ops.current_version(&v1).unwrap(), Some(v2a));
+
+        let unknown = nodalync_crypto::content_hash(b"unknown");
+        assert_eq!(ops.current_version(&unknown).unwrap(), None);
+    }
+
+    #[test]
+    fn test_accept_canonical_version_from_owner_only() {
+        let (owner, _owner_temp) = create_test_ops(OpsConfig::default());
+        let (v1, _, v2b) = create_fork(&owner);
+        let pointer = owner.set_canonical_version(&v2b).unwrap();
+
+        let (peer, _peer_temp) = create_test_ops(OpsConfig::default());
+        assert!(!peer.accept_canonical_version(&pointer, &peer.peer_id()));
+        assert!(peer.accept_canonical_version(&pointer, &owner.peer_id()));
+        assert_eq!(
+            peer.get_canonical_version(&v1).unwrap(),
+            Some(pointer.clone())
+        );
+        // Not newer than the one held
+        assert!(!peer.accept_canonical_version(&pointer, &owner.peer_id()));
+
+        let mut forged = pointer;
+        forged.version = v1;
+        assert!(!peer.accept_canonical_version(&forged, &owner.peer_id()));
+
+        // Nor can a peer choose for a lineage it doesn't hold
+        assert!(matches!(
+            peer.set_canonical_version(&v1),
+            Err(OpsError::ManifestNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,432 @@
+//! Fraud proofs against providers serving the wrong content.
+//!
+//! A provider that sends content in plaintext attaches a signed
+//! [`DeliveryCommitment`] to the bytes it sent. If those bytes don't hash
+//! to what we asked for, the commitment is the provider's own evidence: we
+//! turn it into a [`FraudProof`], apply it, and broadcast it. With
+//! [`FraudProofConfig::submit_on_chain`](crate::FraudProofConfig::submit_on_chain)
+//! the proof is also recorded on-chain.
+//!
+//! Applying a proof, ours or a peer's, costs the provider
+//! [`FraudProofConfig::reputation_penalty`](crate::FraudProofConfig::reputation_penalty)
+//! and slashes its bond if the bond config says to. Each proof is only
+//! applied once.
+//!
+//! Encrypted deliveries carry no commitment, as the served bytes can't be
+//! shown to anyone else. A mismatch there, or a commitment that doesn't
+//! check out, is only acted on locally (see [`crate::bond`]).
+
+use nodalync_crypto::{content_hash, Hash, PeerId};
+use nodalync_store::{FraudProofStore, PeerInfo, PeerStore};
+use nodalync_types::{DeliveryCommitment, FraudProof};
+use nodalync_valid::{
+    sign_delivery, sign_fraud_proof, validate_delivery_commitment, validate_fraud_proof, Validator,
+};
+use nodalync_wire::{FraudProofPayload, QueryResponsePayload};
+use tracing::{debug, info, warn};
+
+use crate::error::OpsResult;
+use crate::events::OpsEvent;
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// List known fraud proofs, newest first, optionally against one
+    /// provider.
+    pub fn list_fraud_proofs(&self, provider: Option<&PeerId>) -> OpsResult<Vec<FraudProof>> {
+        Ok(self.state.fraud_proofs.list(provider)?)
+    }
+
+    /// Apply a fraud proof we made and broadcast it.
+    ///
+    /// The broadcast and the on-chain record are best-effort; the proof
+    /// applies locally either way. Returns whether the proof was new.
+    pub async fn report_fraud(&self, proof: &FraudProof) -> OpsResult<bool> {
+        validate_fraud_proof(proof)?;
+        if !self.apply_fraud_proof(proof).await? {
+            return Ok(false);
+        }
+
+        if let Some(network) = self.network() {
+            let payload = FraudProofPayload {
+                proof: proof.clone(),
+            };
+            if let Err(e) = network.broadcast_fraud_proof(payload).await {
+                warn!(provider = %proof.provider, "Fraud proof broadcast failed (applied locally): {}", e);
+            }
+        }
+
+        if self.config.fraud_proofs.submit_on_chain {
+            self.submit_fraud_proof(proof).await;
+        }
+
+        Ok(true)
+    }
+
+    /// Handle a fraud proof broadcast by a peer.
+    ///
+    /// Validates and applies it, unless we don't accept proofs from
+    /// peers. Returns whether the proof was new.
+    pub async fn handle_fraud_proof(&self, payload: &FraudProofPayload) -> OpsResult<bool> {
+        if !self.config.fraud_proofs.accept_proofs {
+            debug!(provider = %payload.proof.provider, "Ignoring fraud proof (not accepting proofs)");
+            return Ok(false);
+        }
+        validate_fraud_proof(&payload.proof)?;
+        self.apply_fraud_proof(&payload.proof).await
+    }
+
+    /// Commit to the plaintext bytes we are delivering for a query.
+    ///
+    /// Returns `None` without a private key to sign with.
+    pub(crate) fn commit_delivery(
+        &self,
+        hash: &Hash,
+        content: &[u8],
+        payment_id: &Hash,
+        requester: &PeerId,
+    ) -> Option<DeliveryCommitment> {
+        self.private_key()
+            .map(|key| sign_delivery(key, hash, content, payment_id, requester))
+    }
+
+    /// React to a provider serving content that doesn't match its hash.
+    ///
+    /// Reports a fraud proof if the provider committed to what it sent.
+    /// Otherwise only the provider's bond is slashed (if configured),
+    /// with the hash of the bad content as evidence. Failures are logged,
+    /// as the query fails either way.
+    pub(crate) async fn handle_bad_content(
+        &self,
+        provider: &PeerId,
+        hash: &Hash,
+        response: &QueryResponsePayload,
+    ) {
+        let Some(proof) = self.prove_fraud(provider, hash, response) else {
+            self.slash_for_fraud(provider, &content_hash(&response.content))
+                .await;
+            return;
+        };
+        if let Err(e) = self.report_fraud(&proof).await {
+            warn!(provider = %provider, error = %e, "Failed to report fraud proof");
+        }
+    }
+
+    /// Build and sign a fraud proof from a bad delivery, if the provider's
+    /// commitment checks out against the bytes we received.
+    fn prove_fraud(
+        &self,
+        provider: &PeerId,
+        hash: &Hash,
+        response: &QueryResponsePayload,
+    ) -> Option<FraudProof> {
+        let delivery = response.delivery.as_ref()?;
+        let private_key = self.private_key()?;
+        let requester = self.peer_id();
+        let payment_id = response.payment_receipt.payment_id;
+
+        if let Err(e) = validate_delivery_commitment(
+            delivery,
+            hash,
+            &response.content,
+            &payment_id,
+            provider,
+            &requester,
+        ) {
+            debug!(provider = %provider, error = %e, "Delivery commitment does not prove fraud");
+            return None;
+        }
+
+        let mut proof = FraudProof::new(
+            *hash,
+            payment_id,
+            *provider,
+            delivery.clone(),
+            requester,
+            private_key.public_key(),
+            current_timestamp(),
+        );
+        sign_fraud_proof(private_key, &mut proof);
+        Some(proof)
+    }
+
+    /// Store a validated fraud proof and penalize the provider.
+    async fn apply_fraud_proof(&self, proof: &FraudProof) -> OpsResult<bool> {
+        let provider = proof.provider;
+        let stored = self.state.fraud_proofs.store(proof, current_timestamp())?;
+        if !stored {
+            debug!(proof = %proof.hash, "Fraud proof already known");
+            return Ok(false);
+        }
+
+        if provider == self.peer_id() {
+            warn!(content = %proof.content_hash, "A fraud proof was made against us");
+        } else {
+            let mut peer =
+                self.state.peers.get(&provider)?.unwrap_or_else(|| {
+                    PeerInfo::new(provider, proof.delivery.provider_key, vec![], 0)
+                });
+            peer.adjust_reputation(-self.config.fraud_proofs.reputation_penalty);
+            self.state.peers.upsert(&peer)?;
+            self.slash_for_fraud(&provider, &proof.hash).await;
+
+            warn!(
+                provider = %provider,
+                content = %proof.content_hash,
+                reputation = peer.reputation,
+                "Provider proven to have served the wrong content"
+            );
+        }
+
+        self.emit(OpsEvent::FraudProven {
+            provider,
+            content_hash: proof.content_hash,
+            proof_hash: proof.hash,
+        });
+        Ok(true)
+    }
+
+    /// Record a fraud proof on-chain against the provider's account.
+    ///
+    /// Failures are logged; the proof stands locally either way.
+    async fn submit_fraud_proof(&self, proof: &FraudProof) {
+        let Some(settlement) = self.settlement() else {
+            return;
+        };
+        let Some(account) = settlement.get_account_for_peer(&proof.provider) else {
+            debug!(provider = %proof.provider, "No settlement account to record fraud proof against");
+            return;
+        };
+        match settlement.submit_fraud_proof(&account, &proof.hash).await {
+            Ok(tx_id) => {
+                info!(provider = %proof.provider, proof = %proof.hash, tx_id = %tx_id, "Recorded fraud proof on-chain")
+            }
+            Err(e) => {
+                warn!(provider = %proof.provider, error = %e, "Failed to record fraud proof on-chain")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{BondConfig, FraudProofConfig, OpsConfig};
+    use crate::error::OpsError;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key, Signature};
+    use nodalync_settle::{AccountId, Settlement};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_test_utils::MockSettlement;
+    use nodalync_types::{Manifest, Metadata};
+    use nodalync_valid::ValidationError;
+    use nodalync_wire::PaymentReceipt;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn create_test_ops(config: OpsConfig) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops =
+            DefaultNodeOperations::with_config(state, peer_id_from_public_key(&public_key), config);
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    /// A response from `provider` to `requester` serving `served` for a
+    /// query of `requested`.
+    fn response(
+        provider: &DefaultNodeOperations,
+        requester: &PeerId,
+        requested: &[u8],
+        served: &[u8],
+    ) -> QueryResponsePayload {
+        let hash = content_hash(requested);
+        let payment_id = content_hash(b"payment");
+        QueryResponsePayload {
+            hash,
+            content: served.to_vec().into(),
+            manifest: Manifest::new_l0(
+                hash,
+                provider.peer_id(),
+                Metadata::new("Notes", requested.len() as u64),
+                1_000,
+            ),
+            payment_receipt: PaymentReceipt {
+                payment_id,
+                amount: 100,
+                timestamp: 1_000,
+                channel_nonce: 1,
+                distributor_signature: Signature::from_bytes([0u8; 64]),
+                app_fee: 0,
+                app_fee_recipient: None,
+                referral_fee: 0,
+                referrer: None,
+            },
+            content_key: None,
+            bundle: Vec::new(),
+            delivery: provider.commit_delivery(&hash, served, &payment_id, requester),
+            section: None,
+            unit: None,
+            trial_reads_left: None,
+        }
+    }
+
+    fn reputation(ops: &DefaultNodeOperations, peer: &PeerId) -> Option<i64> {
+        ops.state
+            .peers
+            .get(peer)
+            .unwrap()
+            .map(|info| info.reputation)
+    }
+
+    #[tokio::test]
+    async fn test_bad_delivery_proves_fraud() {
+        let (provider, _provider_dir) = create_test_ops(OpsConfig::default());
+        let (mut reader, _reader_dir) = create_test_ops(
+            OpsConfig::default()
+                .with_bonds(BondConfig::default().with_slash_on_fraud(true))
+                .with_fraud_proofs(FraudProofConfig::default().with_submit_on_chain(true)),
+        );
+        let settlement = Arc::new(MockSettlement::new());
+        reader.set_settlement(settlement.clone());
+        settlement.register_peer_account(&provider.peer_id(), AccountId::simple(7));
+        settlement.set_bond(AccountId::simple(7), 1_500);
+        let mut events = reader.subscribe_events();
+
+        let hash = content_hash(b"requested");
+        let bad = response(&provider, &reader.peer_id(), b"requested", b"garbage");
+        reader
+            .handle_bad_content(&provider.peer_id(), &hash, &bad)
+            .await;
+
+        let proofs = reader.list_fraud_proofs(Some(&provider.peer_id())).unwrap();
+        assert_eq!(proofs.len(), 1);
+        let proof = &proofs[0];
+        assert!(validate_fraud_proof(proof).is_ok());
+        assert_eq!(proof.content_hash, hash);
+        assert_eq!(proof.requester, reader.peer_id());
+
+        // The provider loses reputation and its bond, with the proof as evidence
+        assert_eq!(reputation(&reader, &provider.peer_id()), Some(-10));
+        assert_eq!(
+            settlement.slashes(),
+            vec![(AccountId::simple(7), 1_500, proof.hash)]
+        );
+        assert_eq!(
+            settlement.fraud_proof(&proof.hash),
+            Some(AccountId::simple(7))
+        );
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            OpsEvent::FraudProven { provider: p, proof_hash, .. }
+                if p == provider.peer_id() && proof_hash == proof.hash
+        ));
+
+        // Reporting it again changes nothing
+        assert!(!reader.report_fraud(proof).await.unwrap());
+        assert_eq!(reputation(&reader, &provider.peer_id()), Some(-10));
+        assert_eq!(settlement.slashes().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unproven_bad_content() {
+        let (mut provider, _provider_dir) = create_test_ops(OpsConfig::default());
+        let (mut reader, _reader_dir) = create_test_ops(
+            OpsConfig::default().with_bonds(BondConfig::default().with_slash_on_fraud(true)),
+        );
+        let settlement = Arc::new(MockSettlement::new());
+        reader.set_settlement(settlement.clone());
+        settlement.register_peer_account(&provider.peer_id(), AccountId::simple(7));
+        settlement.set_bond(AccountId::simple(7), 1_500);
+        let hash = content_hash(b"requested");
+
+        // A commitment to other bytes than the ones we got proves nothing
+        let mut bad = response(&provider, &reader.peer_id(), b"requested", b"garbage");
+        bad.content = b"other garbage".to_vec().into();
+        reader
+            .handle_bad_content(&provider.peer_id(), &hash, &bad)
+            .await;
+
+        // Nor does a delivery without a commitment
+        provider.clear_private_key();
+        let bad = response(&provider, &reader.peer_id(), b"requested", b"garbage");
+        assert!(bad.delivery.is_none());
+        reader
+            .handle_bad_content(&provider.peer_id(), &hash, &bad)
+            .await;
+
+        // The bond is still slashed on our own word, but nothing is proven
+        assert!(reader.list_fraud_proofs(None).unwrap().is_empty());
+        assert_eq!(reputation(&reader, &provider.peer_id()), None);
+        let slashes = settlement.slashes();
+        assert_eq!(slashes.len(), 1);
+        assert_eq!(slashes[0].2, content_hash(b"other garbage"));
+    }
+
+    #[tokio::test]
+    async fn test_handle_fraud_proof() {
+        let (provider, _provider_dir) = create_test_ops(OpsConfig::default());
+        let (reader, _reader_dir) = create_test_ops(OpsConfig::default());
+        let (peer, _peer_dir) = create_test_ops(OpsConfig::default());
+
+        let hash = content_hash(b"requested");
+        let bad = response(&provider, &reader.peer_id(), b"requested", b"garbage");
+        reader
+            .handle_bad_content(&provider.peer_id(), &hash, &bad)
+            .await;
+        let proof = reader.list_fraud_proofs(None).unwrap().remove(0);
+
+        // A peer that hears the proof penalizes the provider once
+        let payload = FraudProofPayload {
+            proof: proof.clone(),
+        };
+        assert!(peer.handle_fraud_proof(&payload).await.unwrap());
+        assert!(!peer.handle_fraud_proof(&payload).await.unwrap());
+        assert_eq!(reputation(&peer, &provider.peer_id()), Some(-10));
+
+        // A forged proof is rejected
+        let mut forged = proof.clone();
+        forged.delivery.served_hash = content_hash(b"something else");
+        let result = peer
+            .handle_fraud_proof(&FraudProofPayload { proof: forged })
+            .await;
+        assert!(matches!(result, Err(OpsError::Validation(_))));
+
+        // An honest delivery can't be turned into a proof
+        let honest = response(&provider, &reader.peer_id(), b"requested", b"requested");
+        let mut framed = FraudProof::new(
+            hash,
+            honest.payment_receipt.payment_id,
+            provider.peer_id(),
+            honest.delivery.unwrap(),
+            reader.peer_id(),
+            reader.private_key().unwrap().public_key(),
+            current_timestamp(),
+        );
+        sign_fraud_proof(reader.private_key().unwrap(), &mut framed);
+        let result = peer
+            .handle_fraud_proof(&FraudProofPayload { proof: framed })
+            .await;
+        assert!(matches!(
+            result,
+            Err(OpsError::Validation(
+                ValidationError::InvalidFraudProof { .. }
+            ))
+        ));
+        assert_eq!(peer.list_fraud_proofs(None).unwrap().len(), 1);
+
+        // Proofs are ignored when not accepting them
+        let (skeptic, _skeptic_dir) = create_test_ops(
+            OpsConfig::default()
+                .with_fraud_proofs(FraudProofConfig::default().with_accept_proofs(false)),
+        );
+        assert!(!skeptic.handle_fraud_proof(&payload).await.unwrap());
+        assert!(skeptic.list_fraud_proofs(None).unwrap().is_empty());
+    }
+}
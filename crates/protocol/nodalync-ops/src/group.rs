@@ -0,0 +1,386 @@
+//! Group operations.
+//!
+//! A group owner publishes a signed membership list that manifests can
+//! reference from their access control. Content owners keep a copy of
+//! every group their content references: groups they own are
+//! authoritative, while other owners' groups are cached and refetched from
+//! the group owner once the copy is older than
+//! [`OpsConfig::group_cache_ttl_ms`](crate::OpsConfig::group_cache_ttl_ms).
+//! A stale group that cannot be refreshed grants no one access.
+
+use std::collections::HashMap;
+
+use nodalync_crypto::{Hash, PeerId};
+use nodalync_store::{GroupStore, StoredGroup};
+use nodalync_types::{Group, Manifest};
+use nodalync_valid::{sign_group, validate_group, Validator};
+use nodalync_wire::GroupRequestPayload;
+use tracing::{info, warn};
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Create and sign a new group owned by us.
+    ///
+    /// The group ID is derived from our peer ID and `name`, so it stays the
+    /// same as members are added and removed. Requires the private key for
+    /// signing.
+    pub fn create_group(&self, name: impl Into<String>, members: Vec<PeerId>) -> OpsResult<Group> {
+        let private_key = self.private_key().ok_or(OpsError::PrivateKeyRequired)?;
+        let name = name.into();
+        let id = Group::compute_id(&self.peer_id(), &name);
+        if self.state.groups.get(&id)?.is_some() {
+            return Err(OpsError::invalid_operation(format!(
+                "group already exists: {}",
+                name
+            )));
+        }
+
+        let now = current_timestamp();
+        let mut group = Group::new(
+            self.peer_id(),
+            private_key.public_key(),
+            name,
+            members,
+            1,
+            now,
+        );
+        sign_group(private_key, &mut group);
+        validate_group(&group)?;
+
+        self.state.groups.store(&group, now)?;
+        info!(id = %group.id, members = group.members.len(), "Created group");
+        Ok(group)
+    }
+
+    /// Add and remove members of one of our groups.
+    ///
+    /// Bumps the version and re-signs the list; peers holding a cached copy
+    /// pick up the change on their next refresh.
+    pub fn update_group(&self, id: &Hash, add: &[PeerId], remove: &[PeerId]) -> OpsResult<Group> {
+        let private_key = self.private_key().ok_or(OpsError::PrivateKeyRequired)?;
+        let mut group = self
+            .state
+            .groups
+            .get(id)?
+            .ok_or(OpsError::GroupNotFound(*id))?
+            .group;
+        if group.owner != self.peer_id() {
+            return Err(OpsError::AccessDenied);
+        }
+
+        group.members.retain(|m| !remove.contains(m));
+        for peer in add {
+            if !group.members.contains(peer) {
+                group.members.push(*peer);
+            }
+        }
+
+        let now = current_timestamp();
+        group.version += 1;
+        group.updated_at = now;
+        sign_group(private_key, &mut group);
+        validate_group(&group)?;
+
+        self.state.groups.store(&group, now)?;
+        info!(id = %group.id, version = group.version, "Updated group");
+        Ok(group)
+    }
+
+    /// Get a group we own or have cached.
+    pub fn get_group(&self, id: &Hash) -> OpsResult<Option<StoredGroup>> {
+        Ok(self.state.groups.get(id)?)
+    }
+
+    /// List groups we own or have cached.
+    pub fn list_groups(&self) -> OpsResult<Vec<StoredGroup>> {
+        Ok(self.state.groups.list()?)
+    }
+
+    /// Fetch a group from its owner and cache it.
+    ///
+    /// The returned list must be validly signed by `owner`, have the
+    /// requested ID, and be no older than any copy we already hold.
+    pub async fn fetch_group(&self, owner: &PeerId, id: &Hash) -> OpsResult<Group> {
+        let network = self
+            .network()
+            .cloned()
+            .ok_or_else(|| OpsError::invalid_operation("network required to fetch groups"))?;
+        let libp2p_peer = network
+            .libp2p_peer_id(owner)
+            .ok_or(OpsError::PeerIdNotFound)?;
+
+        let group = network
+            .request_group(libp2p_peer, GroupRequestPayload { group_id: *id })
+            .await?
+            .group
+            .ok_or(OpsError::GroupNotFound(*id))?;
+
+        validate_group(&group)?;
+        if group.id != *id || group.owner != *owner {
+            return Err(OpsError::invalid_operation(
+                "owner returned a different group",
+            ));
+        }
+        if let Some(cached) = self.state.groups.get(id)? {
+            if group.version < cached.group.version {
+                return Err(OpsError::invalid_operation(
+                    "owner returned an older group version",
+                ));
+            }
+        }
+
+        self.state.groups.store(&group, current_timestamp())?;
+        Ok(group)
+    }
+
+    /// Groups referenced by a manifest that can be used without a refresh.
+    ///
+    /// Returns the usable groups and the stale cached copies that need to
+    /// be refetched from their owners. Unknown groups are left out.
+    pub(crate) fn cached_groups(
+        &self,
+        manifest: &Manifest,
+    ) -> (HashMap<Hash, Group>, Vec<StoredGroup>) {
+        let mut fresh = HashMap::new();
+        let mut stale = Vec::new();
+        let Some(ids) = &manifest.access.groups else {
+            return (fresh, stale);
+        };
+
+        let me = self.peer_id();
+        let now = current_timestamp();
+        for id in ids {
+            match self.state.groups.get(id) {
+                Ok(Some(stored))
+                    if stored.group.owner == me
+                        || !stored.is_stale(now, self.config().group_cache_ttl_ms) =>
+                {
+                    fresh.insert(*id, stored.group);
+                }
+                Ok(Some(stored)) => stale.push(stored),
+                Ok(None) => {}
+                Err(e) => warn!(group = %id, error = %e, "Failed to load group"),
+            }
+        }
+        (fresh, stale)
+    }
+
+    /// Resolve the groups referenced by a manifest, refreshing stale copies.
+    ///
+    /// Fails closed: a group that cannot be refreshed is left out, so its
+    /// members are treated as non-members.
+    pub(crate) async fn resolve_groups(&self, manifest: &Manifest) -> HashMap<Hash, Group> {
+        let (mut groups, stale) = self.cached_groups(manifest);
+        for stored in stale {
+            let id = stored.group.id;
+            match self.fetch_group(&stored.group.owner, &id).await {
+                Ok(group) => {
+                    groups.insert(id, group);
+                }
+                Err(e) => {
+                    warn!(group = %id, error = %e, "Failed to refresh group; denying its members")
+                }
+            }
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OpsConfig;
+    use crate::{DefaultNodeOperations, Network};
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key, PrivateKey};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_test_utils::MockNetwork;
+    use nodalync_types::{AccessControl, Metadata, Visibility};
+    use nodalync_wire::QueryRequestPayload;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn create_test_ops(config: OpsConfig) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops =
+            DefaultNodeOperations::with_config(state, peer_id_from_public_key(&public_key), config);
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    /// A group signed by a fresh owner.
+    fn foreign_group(members: Vec<PeerId>) -> (Group, PrivateKey) {
+        let (private_key, public_key) = generate_identity();
+        let mut group = Group::new(
+            peer_id_from_public_key(&public_key),
+            public_key,
+            "Reviewers",
+            members,
+            1,
+            current_timestamp(),
+        );
+        sign_group(&private_key, &mut group);
+        (group, private_key)
+    }
+
+    fn request(hash: Hash) -> QueryRequestPayload {
+        QueryRequestPayload {
+            hash,
+            query: None,
+            payment: None,
+            version_spec: None,
+            payment_nonce: 0,
+            capability: None,
+            // Restricted content is only delivered encrypted
+            recipient_key: Some(generate_identity().1),
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        }
+    }
+
+    /// Free Unlisted content restricted to a group.
+    async fn group_content(ops: &mut DefaultNodeOperations, group: &Hash) -> Hash {
+        let content = b"Notes for the group";
+        let hash = ops
+            .create_content(content, Metadata::new("Notes", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Unlisted, 0)
+            .await
+            .unwrap();
+        ops.set_content_access(&hash, AccessControl::with_groups(vec![*group]))
+            .unwrap();
+        hash
+    }
+
+    #[test]
+    fn test_create_and_update_group() {
+        let (mut ops, _temp) = create_test_ops(OpsConfig::default());
+        let alice = test_peer_id();
+        let bob = test_peer_id();
+
+        let group = ops.create_group("Team", vec![alice]).unwrap();
+        assert!(validate_group(&group).is_ok());
+        assert_eq!(group.owner, ops.peer_id());
+        assert_eq!(group.version, 1);
+        assert!(ops.create_group("Team", vec![]).is_err());
+
+        let updated = ops.update_group(&group.id, &[bob], &[alice]).unwrap();
+        assert!(validate_group(&updated).is_ok());
+        assert_eq!(updated.id, group.id);
+        assert_eq!(updated.version, 2);
+        assert_eq!(updated.members, vec![bob]);
+        assert_eq!(ops.get_group(&group.id).unwrap().unwrap().group, updated);
+        assert_eq!(ops.list_groups().unwrap().len(), 1);
+
+        // Only the owner can update a group
+        let (foreign, _) = foreign_group(vec![]);
+        ops.state.groups.store(&foreign, 0).unwrap();
+        assert!(matches!(
+            ops.update_group(&foreign.id, &[bob], &[]),
+            Err(OpsError::AccessDenied)
+        ));
+
+        ops.clear_private_key();
+        assert!(matches!(
+            ops.create_group("Other", vec![]),
+            Err(OpsError::PrivateKeyRequired)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_group_access() {
+        let (mut ops, _temp) = create_test_ops(OpsConfig::default());
+        let member = test_peer_id();
+        let outsider = test_peer_id();
+        let group = ops.create_group("Team", vec![member]).unwrap();
+        let hash = group_content(&mut ops, &group.id).await;
+
+        assert!(ops
+            .handle_query_request(&member, &request(hash))
+            .await
+            .is_ok());
+        assert!(ops
+            .handle_query_request(&outsider, &request(hash))
+            .await
+            .is_err());
+
+        // Removing a member of our own group applies immediately
+        ops.update_group(&group.id, &[], &[member]).unwrap();
+        assert!(ops
+            .handle_query_request(&member, &request(hash))
+            .await
+            .is_err());
+
+        // Unknown groups can't be referenced
+        assert!(matches!(
+            ops.set_content_access(
+                &hash,
+                AccessControl::with_groups(vec![foreign_group(vec![]).0.id])
+            ),
+            Err(OpsError::GroupNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_foreign_group_refresh() {
+        let (mut ops, _temp) = create_test_ops(OpsConfig::default().with_group_cache_ttl(0));
+        let member = test_peer_id();
+        let (group, owner_key) = foreign_group(vec![member]);
+
+        let network = MockNetwork::new().with_group(group.clone());
+        network.register_peer_mapping(nodalync_net::PeerId::random(), group.owner);
+        let network = Arc::new(network);
+        ops.set_network(Arc::clone(&network) as Arc<dyn Network>);
+
+        assert_eq!(
+            ops.fetch_group(&group.owner, &group.id).await.unwrap(),
+            group
+        );
+        let hash = group_content(&mut ops, &group.id).await;
+
+        // Every query refreshes the expired copy from the owner
+        assert!(ops
+            .handle_query_request(&member, &request(hash))
+            .await
+            .is_ok());
+        assert_eq!(network.group_requests().len(), 2);
+
+        // The owner revokes the member
+        let mut revoked = group.clone();
+        revoked.members.clear();
+        revoked.version = 2;
+        sign_group(&owner_key, &mut revoked);
+        network.set_group(revoked);
+        assert!(ops
+            .handle_query_request(&member, &request(hash))
+            .await
+            .is_err());
+        assert_eq!(ops.get_group(&group.id).unwrap().unwrap().group.version, 2);
+
+        // A stale copy that can't be refreshed grants no access
+        let (mut offline, _temp2) = create_test_ops(OpsConfig::default().with_group_cache_ttl(0));
+        offline.state.groups.store(&group, 0).unwrap();
+        let hash = group_content(&mut offline, &group.id).await;
+        assert!(offline
+            .handle_query_request(&member, &request(hash))
+            .await
+            .is_err());
+    }
+}
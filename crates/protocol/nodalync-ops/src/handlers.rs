@@ -4,23 +4,42 @@
 //! processing requests from other nodes.
 
 use nodalync_crypto::{content_hash, Hash, PeerId, PrivateKey, Signature};
-use nodalync_econ::distribute_revenue;
+use nodalync_econ::{distribute_revenue_with_referral, ReferralCut};
 use nodalync_net::NetworkEvent;
-use nodalync_store::{ChannelStore, ContentStore, ManifestStore, PeerStore};
-use nodalync_types::{Channel, ChannelState, Payment, Visibility};
-use nodalync_valid::Validator;
+use nodalync_store::delta::encode_delta;
+use nodalync_store::{
+    AccessKind, AccessLogStore, ChannelStore, ContentStore, DeltaStore, GroupStore, InvoiceStatus,
+    InvoiceStore, LedgerAccount, LedgerEvent, ManifestStore, PaymentDirection, PeerStore,
+    PopularityKind, StoreError, UsageRecord,
+};
+use nodalync_types::{
+    Amount, Channel, ChannelState, ContentType, Manifest, Payment, Timestamp, Visibility,
+};
+use nodalync_valid::{
+    find_version_forks, validate_embargo, validate_invoice_payment, validate_message_basic,
+    validate_message_not_revoked, Validator,
+};
 use nodalync_wire::{
-    decode_message, decode_payload, AnnouncePayload, ChannelAcceptPayload, ChannelCloseAckPayload,
-    ChannelClosePayload, ChannelOpenPayload, MessageType, PaymentReceipt, PreviewRequestPayload,
-    PreviewResponsePayload, QueryRequestPayload, QueryResponsePayload, SearchPayload,
-    SearchResponsePayload, SearchResult as WireSearchResult, VersionInfo, VersionRequestPayload,
-    VersionResponsePayload,
+    decode_message, decode_payload, AnnouncePayload, AvailabilityCheckPayload,
+    ChannelAcceptPayload, ChannelCloseAckPayload, ChannelClosePayload, ChannelOpenPayload,
+    ChannelSyncPayload, ChannelSyncResponsePayload, ChunkRequestPayload, FraudProofPayload,
+    GroupRequestPayload, GroupResponsePayload, InvoiceAckPayload, InvoicePayPayload,
+    InvoicePayload, InvoiceRequestPayload, MessageType, PaymentReceipt, PeerInfoPayload,
+    PingPayload, PongPayload, PreviewRequestPayload, PreviewResponsePayload, QueryRequestPayload,
+    QueryResponsePayload, RelayQueryPayload, ReportPayload, RevocationPayload, SearchPayload,
+    SearchResponsePayload, SearchResult as WireSearchResult, SnapshotRequestPayload,
+    SyncPullPayload, SyncPushPayload, TombstonePayload, UsageReportAckPayload, UsageReportPayload,
+    VersionDelta, VersionInfo, VersionRequestPayload, VersionResponsePayload,
 };
-use tracing::{debug, info, warn};
+use tracing::{debug, field, info, instrument, warn};
 
 use crate::error::{OpsError, OpsResult};
+use crate::events::OpsEvent;
 use crate::extraction::L1Extractor;
-use crate::node_ops::{current_timestamp, NodeOperations};
+use crate::node_ops::{current_timestamp, lock, NodeOperations};
+use crate::revocation::StoredRevocations;
+use crate::section::{priced_manifest, served_size};
+use crate::usage::validate_rating;
 
 impl<V, E> NodeOperations<V, E>
 where
@@ -30,37 +49,83 @@ where
     /// Handle an incoming preview request.
     ///
     /// 1. Load manifest
-    /// 2. Validate access
-    /// 3. Get L1Summary
-    /// 4. Return PreviewResponsePayload
+    /// 2. Validate access (refusing withdrawn content)
+    /// 3. Get the redacted preview summary (and the item list for
+    ///    collections)
+    /// 4. List the versions forking with it, and the owner's canonical
+    ///    pointer if it is forked
+    /// 5. Record the access for analytics and popularity
+    /// 6. Return PreviewResponsePayload, without the redaction rules, and
+    ///    with the paid queries setting a surge price
     pub fn handle_preview_request(
-        &mut self,
-        _requester: &PeerId,
+        &self,
+        requester: &PeerId,
         request: &PreviewRequestPayload,
     ) -> OpsResult<PreviewResponsePayload> {
         // 1. Load manifest
-        let manifest = self
+        let mut manifest = self
             .state
             .manifests
             .load(&request.hash)?
             .ok_or(OpsError::ManifestNotFound(request.hash))?;
 
-        // 2. Validate access (basic visibility check)
+        // 2. Validate access (basic visibility check, embargo)
         if matches!(
             manifest.visibility,
             Visibility::Private | Visibility::Offline
         ) {
             return Err(OpsError::AccessDenied);
         }
+        validate_embargo(&manifest, current_timestamp())?;
+        self.ensure_not_withdrawn(&manifest)?;
+
+        // 3. Get L1Summary, redacted by the preview policy
+        let l1_summary = self.preview_summary(&request.hash)?;
+        manifest.metadata.preview_policy = None;
+        let collection = if manifest.content_type == ContentType::Collection {
+            Some(self.get_collection(&request.hash)?)
+        } else {
+            None
+        };
+
+        // 4. Forks peers could preview, and the owner's canonical choice
+        let now = current_timestamp();
+        let known: Vec<Manifest> = self
+            .state
+            .manifests
+            .get_versions(&manifest.version.root)?
+            .into_iter()
+            .filter(|m| {
+                !matches!(m.visibility, Visibility::Private | Visibility::Offline)
+                    && validate_embargo(m, now).is_ok()
+            })
+            .collect();
+        let forks = find_version_forks(&manifest, &known);
+        let canonical = if manifest.version.forked {
+            self.state.manifests.get_canonical(&manifest.version.root)?
+        } else {
+            None
+        };
 
-        // 3. Get L1Summary
-        let l1_summary = self.extract_l1_summary(&request.hash)?;
+        // 5. Record access
+        self.record_access(requester, &request.hash, AccessKind::Preview, 0);
+        self.record_popularity(&request.hash, PopularityKind::Preview);
 
-        // 4. Return response
+        // 6. Return response, quoting the surge window's paid queries
+        let window_queries = match manifest.economics.pricing {
+            Some(rule) if rule.demand_window(now).is_some() => {
+                Some(self.window_queries(&manifest, now)?)
+            }
+            _ => None,
+        };
         Ok(PreviewResponsePayload {
             hash: request.hash,
             manifest,
             l1_summary,
+            collection,
+            forks,
+            canonical,
+            window_queries,
         })
     }
 
@@ -71,53 +136,154 @@ where
     ///
     /// Flow:
     /// 1. Load manifest
-    /// 2. Validate access
-    /// 3. Validate payment amount
+    /// 2. Validate access, or the capability token if one is attached,
+    ///    refusing withdrawn content either way, and the requester's daily
+    ///    quotas
+    /// 3. Validate payment amount against the current price, with a grace
+    ///    period for dynamic pricing; a query without a payment takes the free
+    ///    fast path (`price == 0` only) and skips steps 4-8, as does one
+    ///    presenting the receipt of an earlier paid query (see
+    ///    [`crate::entitlement`]). Paid queries for challenged content must
+    ///    first answer a query challenge
     /// 4. Validate payment signature for paid content (channel, nonce, signature)
+    ///    and record the payment nonce so it cannot be replayed
     /// 5. Update channel state (credit)
     /// 6. Generate payment ID
     /// 7. Calculate 95/5 distribution (5% synthesis fee to owner, 95% to root L0/L1 contributors)
     /// 8. **IMMEDIATE ON-CHAIN SETTLEMENT** - blocks until confirmed
     /// 9. Update manifest economics (only after settlement)
-    /// 10. Load and return content with receipt
+    /// 10. Load and return content with receipt (plus item contents for a
+    ///     paid collection)
     ///
     /// If settlement fails, the query is REJECTED and no content is delivered.
     /// This ensures creators are always paid before content is released.
     /// The 95/5 distribution split is a CORE PROTOCOL FEATURE.
+    ///
+    /// Queries beyond the concurrency limit are refused with
+    /// [`OpsError::Busy`] before any of this.
+    ///
+    /// Runs in a `handle_query` span carrying the requester's request ID.
+    #[instrument(
+        name = "handle_query",
+        skip_all,
+        fields(
+            request_id = request.request_id.map(field::display),
+            hash = %request.hash,
+            peer = %requester,
+            amount = request.payment.as_ref().map(|payment| payment.amount),
+        )
+    )]
     pub async fn handle_query_request(
-        &mut self,
+        &self,
         requester: &PeerId,
         request: &QueryRequestPayload,
     ) -> OpsResult<QueryResponsePayload> {
+        let _slot = self.acquire_query_slot()?;
         let timestamp = current_timestamp();
-        let payment_amount = request.payment.amount;
 
         // 1. Load manifest
-        let mut manifest = self
+        let manifest = self
             .state
             .manifests
             .load(&request.hash)?
             .ok_or(OpsError::ManifestNotFound(request.hash))?;
 
-        // 2. Validate access
-        if matches!(
-            manifest.visibility,
-            Visibility::Private | Visibility::Offline
-        ) {
-            return Err(OpsError::AccessDenied);
+        // 2. Validate access. A capability token from the owner stands in
+        // for visibility and access lists, but never exposes L2 or content
+        // taken offline or withdrawn.
+        self.ensure_not_withdrawn(&manifest)?;
+        if let Some(token) = &request.capability {
+            if manifest.visibility == Visibility::Offline
+                || manifest.content_type == ContentType::L2
+            {
+                return Err(OpsError::AccessDenied);
+            }
+            self.validator
+                .validate_capability(token, requester, &manifest)?;
+        } else {
+            if matches!(
+                manifest.visibility,
+                Visibility::Private | Visibility::Offline
+            ) {
+                return Err(OpsError::AccessDenied);
+            }
+
+            // Check access control, resolving any groups it references
+            let groups = self.resolve_groups(&manifest).await;
+            self.validator
+                .validate_access_with_groups(requester, &manifest, &groups)?;
+        }
+
+        // Requesters over a daily quota are refused until it resets. The
+        // query is counted now and given back if it isn't served.
+        let size = served_size(request, &manifest)?;
+        let reservation = self.reserve_quota(requester, &manifest, size, timestamp)?;
+        let result = self
+            .serve_query_request(requester, request, manifest, timestamp)
+            .await;
+        if let (Err(_), Some(reservation)) = (&result, reservation) {
+            self.release_quota(requester, reservation);
+        }
+        result
+    }
+
+    /// Serve a query that passed the access and quota checks.
+    async fn serve_query_request(
+        &self,
+        requester: &PeerId,
+        request: &QueryRequestPayload,
+        mut manifest: Manifest,
+        timestamp: Timestamp,
+    ) -> OpsResult<QueryResponsePayload> {
+        // Restricted content is only delivered encrypted, so the requester
+        // must send a key to encrypt it to
+        if manifest.requires_encryption() && request.recipient_key.is_none() {
+            return Err(OpsError::RecipientKeyRequired);
         }
 
-        // Check access control
-        self.validator.validate_access(requester, &manifest)?;
+        // A query for one section or metered unit is priced at its own price
+        let priced = priced_manifest(&manifest, request)?;
+
+        // 3. Validate payment amount. Free content needs no payment at all,
+        // and so no channel or settlement.
+        let Some(payment) = &request.payment else {
+            // Content bought from us earlier is served again to its buyer
+            if let Some(receipt) = &request.entitlement {
+                return self
+                    .handle_entitled_query(requester, request, receipt, manifest, timestamp);
+            }
+            // Paid content may still be read free under its trial policy
+            if nodalync_valid::validate_free_query(&priced).is_err() {
+                return self.handle_trial_query(requester, request, manifest, timestamp);
+            }
+            return self.handle_free_query(requester, request, manifest, timestamp);
+        };
 
-        // 3. Validate payment amount
-        if payment_amount < manifest.economics.price {
+        // Expensive content is only served to a requester proving it holds
+        // the identity key, so captured requests can't be replayed
+        self.ensure_challenge_answered(requester, request, &priced)?;
+        let payment_amount = payment.amount;
+        if payment_amount < self.minimum_accepted_price(&priced, timestamp)? {
             return Err(OpsError::PaymentInsufficient);
         }
 
+        // A referrer named in the query is paid the content's referral cut
+        let referral = match &request.referrer {
+            Some(referrer) => {
+                nodalync_valid::validate_referral(&manifest, referrer, requester)?;
+                manifest
+                    .economics
+                    .referral
+                    .map(|policy| ReferralCut::new(*referrer, policy))
+            }
+            None => None,
+        };
+
         // 4. Validate payment signature for paid content
         // Payment channels are REQUIRED for paid content queries.
-        if manifest.economics.price > 0 {
+        let channel_lock = self.channel_locks.for_peer(requester);
+        let channel_guard = lock(&channel_lock);
+        if priced.economics.price > 0 {
             match self.state.channels.get(requester)? {
                 Some(channel) if channel.is_open() => {
                     // Full payment validation: signature, nonce, amount, provenance
@@ -130,22 +296,41 @@ where
                         .map(|info| info.public_key)
                         .filter(|pk| pk.0 != [0u8; 32]);
 
+                    // Highest nonce already accepted on this channel, which
+                    // survives a crash that left the channel row behind
+                    let last_nonce = self
+                        .state
+                        .channels
+                        .payment_nonces(&channel.channel_id)?
+                        .map(|nonces| nonces.last_received);
+
                     nodalync_valid::validate_payment(
-                        &request.payment,
+                        payment,
                         &channel,
-                        &manifest,
+                        &priced,
                         requester_pubkey.as_ref(),
                         request.payment_nonce,
+                        last_nonce,
                     )
                     .map_err(|e| OpsError::PaymentValidationFailed(e.to_string()))?;
 
-                    // Verify payment nonce is strictly greater than channel nonce (replay prevention)
-                    if request.payment_nonce <= channel.nonce {
-                        return Err(OpsError::PaymentValidationFailed(format!(
-                            "payment nonce {} must be > channel nonce {}",
-                            request.payment_nonce, channel.nonce
-                        )));
-                    }
+                    // Claim the nonce before serving, so a concurrent replay
+                    // of the same payment is rejected
+                    self.state
+                        .channels
+                        .record_payment_nonce(
+                            &channel.channel_id,
+                            requester,
+                            PaymentDirection::Received,
+                            request.payment_nonce,
+                            timestamp,
+                        )
+                        .map_err(|e| match e {
+                            StoreError::StaleNonce { .. } => {
+                                OpsError::PaymentValidationFailed(e.to_string())
+                            }
+                            e => e.into(),
+                        })?;
                 }
                 Some(_) => {
                     // Channel exists but not open - require open channel for paid content
@@ -156,7 +341,7 @@ where
                     tracing::warn!(
                         requester = %requester,
                         hash = %request.hash,
-                        price = manifest.economics.price,
+                        price = priced.economics.price,
                         "Paid content requested without payment channel"
                     );
                     return Err(OpsError::ChannelRequired);
@@ -165,6 +350,7 @@ where
         }
 
         // 5. Update channel state (credit - they pay us)
+        let mut payable = None;
         if let Some(mut channel) = self.state.channels.get(requester)? {
             if channel.is_open() && payment_amount > 0 {
                 // Create payment record
@@ -217,22 +403,39 @@ where
                     channel.nonce = request.payment_nonce;
                 }
 
-                self.state.channels.update(requester, &channel)?;
+                self.commit_channel_state(requester, &channel)?;
                 self.state.channels.add_payment(requester, payment)?;
+                payable = Some(self.record_query_revenue(
+                    request.hash,
+                    payment_amount,
+                    &manifest.owner,
+                    &manifest.provenance.root_l0l1,
+                    referral.as_ref(),
+                ));
             }
         }
 
+        drop(channel_guard);
+
         // 6. Generate payment ID
-        let payment_id =
-            content_hash(&[request.hash.0.as_slice(), &timestamp.to_be_bytes()].concat());
+        let payment_id = nodalync_valid::receipt_payment_id(
+            &request.hash,
+            requester,
+            timestamp,
+            request.payment_nonce,
+        );
 
         // 7. Calculate 95/5 distribution (CORE PROTOCOL FEATURE)
         // - 5% synthesis fee goes to the content owner
         // - 95% root pool is distributed proportionally to foundational L0/L1 contributors
-        let distributions = distribute_revenue(
+        // The app fee and any referral cut come off the top first.
+        let app_fee = self.config.app_fee;
+        let distributions = distribute_revenue_with_referral(
             payment_amount,
             &manifest.owner,
             &manifest.provenance.root_l0l1,
+            app_fee.as_ref(),
+            referral.as_ref(),
         );
 
         // Log the distribution split for transparency
@@ -260,8 +463,8 @@ where
         let transaction_id = if payment_amount > 0 {
             if let Some(settlement) = self.settlement().cloned() {
                 // Create a single-payment batch for immediate settlement
-                // Note: create_settlement_batch internally calls distribute_revenue
-                // to compute the same 95/5 split for all recipients
+                // Note: the batch is split with distribute_revenue_with_referral,
+                // computing the same fees and 95/5 split for all recipients
                 let settle_sig = match self.private_key() {
                     Some(pk) => {
                         let tmp = Payment::new(
@@ -290,7 +493,11 @@ where
                     settle_sig,
                 );
 
-                let batch = nodalync_econ::create_settlement_batch(&[payment]);
+                let batch = nodalync_econ::create_settlement_batch_with_referral(
+                    &[payment],
+                    app_fee.as_ref(),
+                    referral.as_ref(),
+                );
 
                 // Submit to chain and WAIT for confirmation (with timeout)
                 let settlement_timeout =
@@ -308,6 +515,15 @@ where
                             hash = %request.hash,
                             "Payment settled on-chain with 95/5 distribution before content delivery"
                         );
+                        if let Some(payable) = payable {
+                            self.record_ledger_transfer(
+                                LedgerEvent::SettlementPayout,
+                                &tx_id,
+                                LedgerAccount::Contract,
+                                LedgerAccount::Payable,
+                                payable,
+                            );
+                        }
                         Some(tx_id.to_string())
                     }
                     Ok(Err(e)) => {
@@ -347,61 +563,208 @@ where
             None // Free content, no settlement needed
         };
 
-        // 9. Update manifest economics (only after successful settlement)
-        manifest.economics.record_query(payment_amount);
+        // 9. Update manifest economics (only after successful settlement).
+        // A metered read counts as one query, on its first unit.
+        if request.unit.unwrap_or(0) == 0 {
+            manifest.economics.record_query(payment_amount);
+        } else {
+            manifest.economics.total_revenue += payment_amount;
+        }
         manifest.updated_at = timestamp;
         self.state.manifests.update(&manifest)?;
+        self.record_access(requester, &request.hash, AccessKind::Query, payment_amount);
+        self.record_popularity(&request.hash, PopularityKind::Query);
+
+        // 10. Load and return content (settlement confirmed); large blobs
+        // are mapped rather than copied onto the heap
+        let content = self.load_served_content(request, &manifest)?;
+        let (content, content_key) =
+            self.seal_content(&manifest, content, request.recipient_key.as_ref())?;
+
+        // A paid collection is a bundle purchase: deliver every item
+        let mut bundle =
+            if manifest.content_type == ContentType::Collection && manifest.economics.price > 0 {
+                self.load_bundle(&self.get_collection(&request.hash)?)?
+            } else {
+                Vec::new()
+            };
+        for item in &mut bundle {
+            let content = std::mem::take(&mut item.content);
+            let (content, content_key) = self.seal_content(
+                &item.manifest,
+                content.into(),
+                request.recipient_key.as_ref(),
+            )?;
+            item.content = content.into_vec();
+            item.content_key = content_key;
+        }
 
-        // 10. Load and return content (settlement confirmed)
-        let content = self
-            .state
-            .content
-            .load(&request.hash)?
-            .ok_or(OpsError::NotFound(request.hash))?;
-
-        let receipt_sig = match self.private_key() {
-            Some(pk) => {
-                let msg = nodalync_valid::construct_receipt_message(
-                    &payment_id,
-                    payment_amount,
-                    timestamp,
-                    request.payment_nonce,
-                );
-                nodalync_crypto::sign(pk, &msg)
-            }
-            None => Signature::from_bytes([0u8; 64]),
-        };
+        let receipt_sig = self.sign_receipt(
+            &payment_id,
+            payment_amount,
+            timestamp,
+            request.payment_nonce,
+        );
+        let app_fee_amount = app_fee.map_or(0, |fee| fee.amount(payment_amount));
+        let referral_amount = referral.map_or(0, |cut| cut.amount(payment_amount));
         let receipt = PaymentReceipt {
             payment_id,
             amount: payment_amount,
             timestamp,
             channel_nonce: request.payment_nonce,
             distributor_signature: receipt_sig,
+            app_fee: app_fee_amount,
+            app_fee_recipient: app_fee
+                .filter(|_| app_fee_amount > 0)
+                .map(|fee| fee.recipient),
+            referral_fee: referral_amount,
+            referrer: referral
+                .filter(|_| referral_amount > 0)
+                .map(|cut| cut.referrer),
+        };
+
+        // Delivery commitments cover whole content, not a section or unit
+        let delivery = match (&content_key, request.section, request.unit) {
+            (None, None, None) => {
+                self.commit_delivery(&request.hash, &content, &payment_id, requester)
+            }
+            _ => None,
         };
 
+        // The receipt lets the requester re-download the content later
+        self.grant_entitlement(requester, request, &receipt);
+
         tracing::info!(
             hash = %request.hash,
             payment_amount = payment_amount,
             transaction_id = ?transaction_id,
             "Content delivered after settlement confirmation"
         );
+        if payment_amount > 0 {
+            self.emit(OpsEvent::PaymentReceived {
+                payment_id,
+                payer: *requester,
+                content_hash: request.hash,
+                amount: payment_amount,
+                transaction_id,
+            });
+        }
+        self.emit(OpsEvent::ContentQueried {
+            content_hash: request.hash,
+            requester: *requester,
+            amount: payment_amount,
+        });
+
+        Ok(QueryResponsePayload {
+            hash: request.hash,
+            content,
+            manifest,
+            payment_receipt: receipt,
+            bundle,
+            content_key,
+            delivery,
+            section: request.section,
+            unit: request.unit,
+            trial_reads_left: None,
+        })
+    }
+
+    /// Serve a query for free content that carries no payment.
+    ///
+    /// There is no channel to credit and nothing to distribute or settle,
+    /// but the query is still counted on the manifest and in the access log
+    /// so publishers see usage of free material.
+    pub(crate) fn handle_free_query(
+        &self,
+        requester: &PeerId,
+        request: &QueryRequestPayload,
+        mut manifest: Manifest,
+        timestamp: Timestamp,
+    ) -> OpsResult<QueryResponsePayload> {
+        let content = self.load_served_content(request, &manifest)?;
+
+        manifest.economics.record_query(0);
+        manifest.updated_at = timestamp;
+        self.state.manifests.update(&manifest)?;
+        self.record_access(requester, &request.hash, AccessKind::Query, 0);
+        self.record_popularity(&request.hash, PopularityKind::Query);
+
+        let payment_id = nodalync_valid::receipt_payment_id(&request.hash, requester, timestamp, 0);
+        let receipt = PaymentReceipt {
+            payment_id,
+            amount: 0,
+            timestamp,
+            channel_nonce: 0,
+            distributor_signature: self.sign_receipt(&payment_id, 0, timestamp, 0),
+            app_fee: 0,
+            app_fee_recipient: None,
+            referral_fee: 0,
+            referrer: None,
+        };
+
+        let (content, content_key) =
+            self.seal_content(&manifest, content, request.recipient_key.as_ref())?;
+        // Delivery commitments cover whole content, not a section or unit
+        let delivery = match (&content_key, request.section, request.unit) {
+            (None, None, None) => {
+                self.commit_delivery(&request.hash, &content, &payment_id, requester)
+            }
+            _ => None,
+        };
+
+        debug!(hash = %request.hash, requester = %requester, "Served free query");
+        self.emit(OpsEvent::ContentQueried {
+            content_hash: request.hash,
+            requester: *requester,
+            amount: 0,
+        });
 
         Ok(QueryResponsePayload {
             hash: request.hash,
             content,
             manifest,
             payment_receipt: receipt,
+            bundle: Vec::new(),
+            content_key,
+            delivery,
+            section: request.section,
+            unit: request.unit,
+            trial_reads_left: None,
         })
     }
 
+    /// Sign a query receipt, or return a zero signature without a key.
+    pub(crate) fn sign_receipt(
+        &self,
+        payment_id: &Hash,
+        amount: Amount,
+        timestamp: Timestamp,
+        channel_nonce: u64,
+    ) -> Signature {
+        match self.private_key() {
+            Some(pk) => {
+                let msg = nodalync_valid::construct_receipt_message(
+                    payment_id,
+                    amount,
+                    timestamp,
+                    channel_nonce,
+                );
+                nodalync_crypto::sign(pk, &msg)
+            }
+            None => Signature::from_bytes([0u8; 64]),
+        }
+    }
+
     /// Handle an incoming version request.
     ///
     /// 1. Get all versions for root
     /// 2. Find latest if requested
-    /// 3. Return VersionResponsePayload
+    /// 3. Attach a delta from `delta_from` to the latest version, if available
+    /// 4. Attach the manifests peers could preview, if requested
+    /// 5. Return VersionResponsePayload
     pub fn handle_version_request(
         &self,
-        _requester: &PeerId,
+        requester: &PeerId,
         request: &VersionRequestPayload,
     ) -> OpsResult<VersionResponsePayload> {
         // Get all versions
@@ -418,27 +781,109 @@ where
             })
             .collect();
 
-        // Find latest version hash
-        let latest = versions
-            .iter()
-            .max_by_key(|v| v.number)
-            .map(|v| v.hash)
+        // Find latest version hash, resolving forks by the fork policy
+        let latest = self
+            .resolve_current_version(&request.version_root, &manifests)?
             .unwrap_or(request.version_root);
 
+        // Attach a delta if the requester already has an older version
+        let delta = match request.delta_from {
+            Some(base) if base != latest && manifests.iter().any(|m| m.hash == base) => {
+                self.version_delta(requester, &base, &latest)?
+            }
+            _ => None,
+        };
+
+        // Manifests for chain repair, on the same terms as previews
+        let manifests = if request.include_manifests {
+            let now = current_timestamp();
+            manifests
+                .into_iter()
+                .filter(|m| {
+                    !matches!(m.visibility, Visibility::Private | Visibility::Offline)
+                        && validate_embargo(m, now).is_ok()
+                        && self.ensure_not_withdrawn(m).is_ok()
+                })
+                .map(|mut m| {
+                    m.metadata.preview_policy = None;
+                    m
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
         Ok(VersionResponsePayload {
             version_root: request.version_root,
             versions,
             latest,
+            delta,
+            manifests,
+            canonical: self.state.manifests.get_canonical(&request.version_root)?,
         })
     }
 
+    /// Build a delta from `base` to the `target` version for a requester.
+    ///
+    /// Deltas hand over the full content in plaintext, so they are only
+    /// served for free, openly shared versions the requester could otherwise
+    /// query. Paid and restricted versions must be fetched with a query. Uses the stored delta if there is one, otherwise
+    /// encodes it from the local blobs. Returns `None` if no delta can be
+    /// served.
+    fn version_delta(
+        &self,
+        requester: &PeerId,
+        base: &Hash,
+        target: &Hash,
+    ) -> OpsResult<Option<VersionDelta>> {
+        let manifest = match self.state.manifests.load(target)? {
+            Some(manifest) => manifest,
+            None => return Ok(None),
+        };
+
+        if manifest.requires_encryption()
+            || manifest.economics.price > 0
+            || self
+                .validator
+                .validate_access_with_groups(requester, &manifest, &self.cached_groups(&manifest).0)
+                .is_err()
+            || validate_embargo(&manifest, current_timestamp()).is_err()
+        {
+            return Ok(None);
+        }
+
+        let delta = match self.state.content.load_delta(base, target)? {
+            Some(delta) => delta,
+            None => {
+                let (Some(base_content), Some(content)) = (
+                    self.state.content.load(base)?,
+                    self.state.content.load(target)?,
+                ) else {
+                    return Ok(None);
+                };
+                match encode_delta(&base_content, &content) {
+                    Ok(delta) => delta,
+                    Err(e) => {
+                        warn!(base = %base, target = %target, error = %e, "Failed to encode version delta");
+                        return Ok(None);
+                    }
+                }
+            }
+        };
+
+        Ok(Some(VersionDelta {
+            base: *base,
+            manifest,
+            delta,
+        }))
+    }
+
     /// Handle an incoming search request.
     ///
-    /// 1. Search local manifests matching query
-    /// 2. Apply filters (content type, max price)
-    /// 3. Return SearchResponsePayload with results
+    /// 1. Search local shared manifests matching the query and filters
+    /// 2. Return SearchResponsePayload with results
     pub fn handle_search_request(
-        &mut self,
+        &self,
         _requester: &PeerId,
         request: &SearchPayload,
     ) -> OpsResult<SearchResponsePayload> {
@@ -449,22 +894,24 @@ where
         let limit = request.limit.min(100);
 
         // Build filter for shared content only
-        let mut filter = ManifestFilter::new()
+        let mut filter = ManifestFilter::new();
+        if let Some(ref filters) = request.filters {
+            filter = filter.with_search_filters(filters);
+        }
+        let filter = filter
             .with_text_query(&query)
             .with_visibility(Visibility::Shared)
             .limit(limit);
 
-        // Apply content type filter if specified
-        if let Some(ref filters) = request.filters {
-            if let Some(ref content_types) = filters.content_types {
-                if let Some(ct) = content_types.first() {
-                    filter = filter.with_content_type(*ct);
-                }
-            }
-        }
-
-        // Search local manifests
-        let manifests = self.state.manifests.list(filter)?;
+        // Search local manifests, hiding content that is still embargoed
+        let now = current_timestamp();
+        let manifests: Vec<_> = self
+            .state
+            .manifests
+            .list(filter)?
+            .into_iter()
+            .filter(|m| !m.access.is_embargoed(now))
+            .collect();
 
         // Get our listen addresses to include in results for reconnection
         let publisher_addresses: Vec<String> = self
@@ -476,9 +923,9 @@ where
         let results: Vec<WireSearchResult> = manifests
             .iter()
             .map(|m| {
-                // Extract L1 summary if available
+                // Extract the (redacted) preview summary if available
                 let l1_summary = self
-                    .extract_l1_summary(&m.hash)
+                    .preview_summary(&m.hash)
                     .unwrap_or_else(|_| L1Summary::empty(m.hash));
 
                 // Calculate simple relevance score based on title match
@@ -502,21 +949,10 @@ where
             })
             .collect();
 
-        // Apply max_price filter if specified
-        let results = if let Some(ref filters) = request.filters {
-            if let Some(max_price) = filters.max_price {
-                results
-                    .into_iter()
-                    .filter(|r| r.price <= max_price)
-                    .collect()
-            } else {
-                results
-            }
-        } else {
-            results
-        };
-
         let total_count = results.len() as u64;
+        for result in &results {
+            self.record_popularity(&result.hash, PopularityKind::SearchHit);
+        }
 
         Ok(SearchResponsePayload {
             results,
@@ -541,7 +977,7 @@ where
     /// 5. Create channel state with capped deposit
     /// 6. Return ChannelAcceptPayload with our Hedera account
     pub async fn handle_channel_open(
-        &mut self,
+        &self,
         requester: &PeerId,
         request: &ChannelOpenPayload,
     ) -> OpsResult<ChannelAcceptPayload> {
@@ -614,6 +1050,13 @@ where
                                     info!(tx_id = %tx_id, "Auto-deposit successful for channel acceptance");
                                     // Record the deposit time (SECURITY: sets cooldown)
                                     self.mark_auto_deposit();
+                                    self.record_ledger_transfer(
+                                        LedgerEvent::ContractDeposit,
+                                        tx_id,
+                                        LedgerAccount::External,
+                                        LedgerAccount::Contract,
+                                        deposit_amount,
+                                    );
                                 }
                                 Err(e) => {
                                     warn!(error = %e, "Auto-deposit failed, channel acceptance may fail");
@@ -644,6 +1087,7 @@ where
         );
 
         self.state.channels.create(requester, channel)?;
+        self.record_channel_deposit(&request.channel_id, my_deposit);
 
         // 6. Return accept payload with our Hedera account
         let hedera_account = self.settlement().map(|s| s.get_own_account_string());
@@ -662,7 +1106,7 @@ where
     /// This is called on the initiator side when they receive the
     /// ChannelAccept message from the responder.
     pub fn handle_channel_accept(
-        &mut self,
+        &self,
         peer: &PeerId,
         response: &ChannelAcceptPayload,
     ) -> OpsResult<()> {
@@ -726,7 +1170,7 @@ where
     ///
     /// The initiator will then submit both signatures to the chain.
     pub fn handle_channel_close_request(
-        &mut self,
+        &self,
         requester: &PeerId,
         request: &ChannelClosePayload,
         private_key: &PrivateKey,
@@ -857,6 +1301,7 @@ where
         channel.pending_close = Some(pending_close);
         channel.mark_closing(timestamp);
         self.state.channels.update(requester, &channel)?;
+        self.record_channel_close(&channel.channel_id, channel.my_balance);
 
         debug!(
             channel_id = %request.channel_id,
@@ -869,299 +1314,835 @@ where
         })
     }
 
-    /// Handle a broadcast announcement from GossipSub.
-    ///
-    /// When we receive an announcement, we:
-    /// 1. Decode the AnnouncePayload
-    /// 2. Log the announcement for debugging
-    /// 3. Store it in the announcements cache for later lookup
+    /// Handle an incoming channel sync request.
     ///
-    /// This allows preview/query to discover content from remote nodes.
-    fn handle_broadcast_announcement(&mut self, topic: &str, data: &[u8]) -> OpsResult<()> {
-        // Only process announcements on the announce topic
-        if !topic.contains("/nodalync/announce") {
-            return Ok(());
-        }
+    /// Sent by a peer recovering from a crash. Adopts their state if it is
+    /// ahead of ours, then responds with our latest signed state so they can
+    /// catch up if we are ahead.
+    pub fn handle_channel_sync(
+        &self,
+        requester: &PeerId,
+        request: &ChannelSyncPayload,
+    ) -> OpsResult<ChannelSyncResponsePayload> {
+        self.apply_channel_sync(
+            requester,
+            &request.channel_id,
+            request.nonce,
+            &request.balances,
+            &request.signature,
+        )?;
 
-        // Try to decode the wire protocol message
-        match decode_message(data) {
-            Ok(message) => {
-                // Check if this is an ANNOUNCE message
-                if message.message_type != MessageType::Announce {
-                    debug!(
-                        "Ignoring non-announce broadcast message: {:?}",
-                        message.message_type
-                    );
-                    return Ok(());
-                }
+        let channel = self
+            .state
+            .channels
+            .get(requester)?
+            .ok_or(OpsError::ChannelNotFound)?;
+        let state = self.channel_sync_payload(&channel);
 
-                // Decode the AnnouncePayload from the message payload
-                match decode_payload::<AnnouncePayload>(&message.payload) {
-                    Ok(payload) => {
-                        info!(
-                            hash = %payload.hash,
-                            title = %payload.title,
-                            price = payload.price,
-                            addresses = ?payload.addresses,
-                            "Received content announcement"
-                        );
+        debug!(
+            channel_id = %request.channel_id,
+            their_nonce = request.nonce,
+            our_nonce = state.nonce,
+            "Answered channel sync request"
+        );
 
-                        // Store the announcement in our cache for later lookup
-                        // This allows preview/query to find content from remote nodes
-                        self.state.store_announcement(payload);
-                        Ok(())
-                    }
-                    Err(e) => {
-                        debug!("Failed to decode announcement payload: {}", e);
-                        Ok(()) // Don't fail on decode errors
-                    }
-                }
-            }
-            Err(e) => {
-                debug!("Failed to decode broadcast message: {}", e);
-                Ok(()) // Don't fail on decode errors
-            }
+        Ok(ChannelSyncResponsePayload {
+            channel_id: state.channel_id,
+            nonce: state.nonce,
+            balances: state.balances,
+            signature: state.signature,
+        })
+    }
+
+    /// Handle a usage report for our content.
+    ///
+    /// Requires `UsageReportConfig::accept`. Reports are rate-limited per
+    /// sender, checked against the content's size, and stored without the
+    /// sender. Refusals are returned in the ack rather than as errors.
+    pub fn handle_usage_report(
+        &self,
+        sender: &PeerId,
+        report: &UsageReportPayload,
+    ) -> UsageReportAckPayload {
+        let result = self.record_usage_report(sender, report);
+        usage_report_ack(report.hash, result)
+    }
+
+    fn record_usage_report(&self, sender: &PeerId, report: &UsageReportPayload) -> OpsResult<()> {
+        if !self.config.usage_reports.accept {
+            return Err(OpsError::UsageReportRejected(
+                "not accepting usage reports".to_string(),
+            ));
+        }
+        validate_rating(report.rating)?;
+
+        let manifest = self
+            .state
+            .manifests
+            .load(&report.hash)?
+            .filter(|m| m.owner == self.peer_id())
+            .ok_or(OpsError::NotFound(report.hash))?;
+        if report.bytes_read > manifest.metadata.content_size {
+            return Err(OpsError::invalid_operation(
+                "bytes read exceeds content size",
+            ));
+        }
+        if !lock(&self.usage_report_limiter)
+            .check(sender, self.config.usage_reports.max_per_peer_per_hour)
+        {
+            return Err(OpsError::UsageReportRejected(
+                "rate limit exceeded".to_string(),
+            ));
         }
+
+        self.state.access_log.record_usage(&UsageRecord {
+            content_hash: report.hash,
+            bytes_read: report.bytes_read,
+            context_tokens: report.context_tokens,
+            rating: report.rating,
+            timestamp: current_timestamp(),
+        })?;
+        debug!(hash = %report.hash, bytes_read = report.bytes_read, "Recorded usage report");
+        Ok(())
     }
 
-    /// Handle an incoming network event.
+    /// Handle an invoice sent to us by its payee.
     ///
-    /// This is the main entry point for processing network events and
-    /// dispatching to the appropriate handler. Returns an optional response
-    /// that should be encoded and sent back to the peer.
+    /// Validates the invoice and tracks it as received so it can be paid.
+    pub fn handle_invoice(
+        &self,
+        sender: &PeerId,
+        payload: &InvoicePayload,
+    ) -> OpsResult<InvoiceAckPayload> {
+        let invoice_hash = payload.invoice.hash;
+        if self.receive_invoice(payload.invoice.clone())? {
+            info!(
+                hash = %invoice_hash,
+                sender = %sender,
+                amount = payload.invoice.amount,
+                "Received invoice"
+            );
+        }
+
+        Ok(InvoiceAckPayload {
+            invoice_hash,
+            accepted: true,
+            reason: None,
+        })
+    }
+
+    /// Handle a request for one of our invoices.
     ///
-    /// The response is returned as (MessageType, serialized_payload) for the
-    /// caller to construct the actual Message envelope.
-    pub async fn handle_network_event(
-        &mut self,
-        event: NetworkEvent,
-    ) -> OpsResult<Option<(MessageType, Vec<u8>)>> {
-        match event {
-            NetworkEvent::InboundRequest { peer, data, .. } => {
-                // First decode the full wire message (header + payload + signature)
-                let message = match decode_message(&data) {
-                    Ok(msg) => msg,
-                    Err(e) => {
-                        debug!(
-                            "Failed to decode message: {}, data length: {}",
-                            e,
-                            data.len()
-                        );
-                        return Ok(None);
-                    }
-                };
+    /// Returns `None` if we did not issue an invoice with that hash.
+    pub fn handle_invoice_request(
+        &self,
+        requester: &PeerId,
+        request: &InvoiceRequestPayload,
+    ) -> OpsResult<Option<InvoicePayload>> {
+        let invoice = match self.issued_invoice(&request.invoice_hash) {
+            Ok(record) => record.invoice,
+            Err(OpsError::InvoiceNotFound(_)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        debug!(hash = %invoice.hash, requester = %requester, "Answered invoice request");
+        Ok(Some(InvoicePayload { invoice }))
+    }
 
-                debug!(
-                    "Received message type {:?} from peer {}, sender {:?}, payload length: {}",
-                    message.message_type,
-                    peer,
-                    message.sender,
-                    message.payload.len()
-                );
+    /// Handle a request for one of our groups.
+    ///
+    /// Only groups we own are served; cached copies of other owners' groups
+    /// are never passed on.
+    pub fn handle_group_request(
+        &self,
+        requester: &PeerId,
+        request: &GroupRequestPayload,
+    ) -> OpsResult<GroupResponsePayload> {
+        let group = self
+            .state
+            .groups
+            .get(&request.group_id)?
+            .map(|stored| stored.group)
+            .filter(|group| group.owner == self.peer_id());
+        debug!(id = %request.group_id, requester = %requester, found = group.is_some(), "Answered group request");
+        Ok(GroupResponsePayload { group })
+    }
 
-                // SECURITY: Verify message signature before trusting sender identity.
-                let sender_pubkey = self
-                    .state
-                    .peers
-                    .get(&message.sender)
-                    .ok()
-                    .flatten()
-                    .map(|info| info.public_key)
-                    .filter(|pk| pk.0 != [0u8; 32]);
-
-                if let Some(pubkey) = &sender_pubkey {
-                    if !nodalync_wire::verify_message_signature(&message, pubkey) {
-                        tracing::warn!(
-                            sender = %message.sender,
-                            msg_type = ?message.message_type,
-                            "Message signature verification FAILED - rejecting"
-                        );
-                        return Ok(None);
+    /// Handle a payment for one of our invoices.
+    ///
+    /// 1. Load the unpaid, unexpired invoice
+    /// 2. Validate the payment against the invoice and the payer's channel,
+    ///    and record the payment nonce so it cannot be replayed
+    /// 3. Credit the channel and mark the invoice paid
+    pub fn handle_invoice_payment(
+        &self,
+        payer: &PeerId,
+        request: &InvoicePayPayload,
+    ) -> OpsResult<InvoiceAckPayload> {
+        let timestamp = current_timestamp();
+
+        // 1. Load the invoice
+        let record = self.issued_invoice(&request.invoice_hash)?;
+        if record.status == InvoiceStatus::Paid {
+            return Err(OpsError::invalid_operation("invoice already paid"));
+        }
+        if record.is_expired(timestamp) {
+            return Err(OpsError::invalid_operation("invoice expired"));
+        }
+
+        // 2. Validate the payment
+        let channel_lock = self.channel_locks.for_peer(payer);
+        let _guard = lock(&channel_lock);
+        let mut channel = self
+            .state
+            .channels
+            .get(payer)?
+            .ok_or(OpsError::ChannelRequired)?;
+        let payer_pubkey = self
+            .state
+            .peers
+            .get(payer)
+            .ok()
+            .flatten()
+            .map(|info| info.public_key)
+            .filter(|pk| pk.0 != [0u8; 32]);
+        let last_nonce = self
+            .state
+            .channels
+            .payment_nonces(&channel.channel_id)?
+            .map(|nonces| nonces.last_received);
+
+        validate_invoice_payment(
+            &request.payment,
+            &record.invoice,
+            &channel,
+            payer_pubkey.as_ref(),
+            request.payment_nonce,
+            last_nonce,
+        )
+        .map_err(|e| OpsError::PaymentValidationFailed(e.to_string()))?;
+
+        self.state
+            .channels
+            .record_payment_nonce(
+                &channel.channel_id,
+                payer,
+                PaymentDirection::Received,
+                request.payment_nonce,
+                timestamp,
+            )
+            .map_err(|e| match e {
+                StoreError::StaleNonce { .. } => OpsError::PaymentValidationFailed(e.to_string()),
+                e => e.into(),
+            })?;
+
+        // 3. Credit the channel and mark the invoice paid
+        let payment = request.payment.clone();
+        channel
+            .receive(payment.clone(), timestamp)
+            .map_err(|_| OpsError::InsufficientChannelBalance)?;
+        channel.nonce = channel.nonce.max(request.payment_nonce);
+        self.commit_channel_state(payer, &channel)?;
+        self.state.channels.add_payment(payer, payment.clone())?;
+
+        self.state
+            .invoices
+            .mark_paid(&request.invoice_hash, payer, &payment.id, timestamp)?;
+        self.record_ledger_transfer(
+            LedgerEvent::InvoiceReceived,
+            request.invoice_hash,
+            LedgerAccount::Revenue,
+            LedgerAccount::Channels,
+            payment.amount,
+        );
+        info!(
+            hash = %request.invoice_hash,
+            payer = %payer,
+            amount = payment.amount,
+            "Invoice paid"
+        );
+
+        Ok(InvoiceAckPayload {
+            invoice_hash: request.invoice_hash,
+            accepted: true,
+            reason: None,
+        })
+    }
+
+    /// Handle a broadcast announcement from GossipSub.
+    ///
+    /// When we receive an announcement, we:
+    /// 1. Decode the AnnouncePayload
+    /// 2. Log the announcement for debugging
+    /// 3. Check the publisher signature, dropping it if invalid
+    /// 4. Store it in the announcements cache for later lookup
+    ///
+    /// This allows preview/query to discover content from remote nodes.
+    /// TOMBSTONE messages on the same topic withdraw content instead (see
+    /// [`Self::handle_tombstone`]), REPORT messages feed moderation (see
+    /// [`Self::handle_report`]), FRAUD_PROOF messages penalize providers
+    /// (see [`Self::handle_fraud_proof`]), and REVOCATION messages revoke
+    /// keys (see [`Self::handle_revocation`]). Other messages sent by
+    /// revoked keys are dropped.
+    async fn handle_broadcast_announcement(&self, topic: &str, data: &[u8]) -> OpsResult<()> {
+        // Only process announcements on the announce topic
+        if !topic.contains("/nodalync/announce") {
+            return Ok(());
+        }
+
+        // Try to decode the wire protocol message
+        match decode_message(data) {
+            Ok(message) => {
+                // Revocations are relayed by anyone, the revoked key included
+                if message.message_type == MessageType::Revocation {
+                    match decode_payload::<RevocationPayload>(&message.payload) {
+                        Ok(payload) => {
+                            if let Err(e) = self.handle_revocation(&payload) {
+                                warn!(peer = %payload.certificate.peer_id, error = %e, "Ignoring invalid revocation");
+                            }
+                        }
+                        Err(e) => debug!("Failed to decode revocation payload: {}", e),
                     }
-                } else {
-                    // Peer key not yet known — soft-fail during bootstrap.
-                    tracing::debug!(
-                        sender = %message.sender,
-                        "No public key for sender - skipping signature verification"
-                    );
+                    return Ok(());
                 }
 
-                let nodalync_peer = message.sender;
+                if self.is_peer_revoked(&message.sender) {
+                    debug!(sender = %message.sender, "Dropping broadcast from revoked key");
+                    return Ok(());
+                }
 
-                // Handle the request based on message type
-                match message.message_type {
-                    MessageType::PreviewRequest => {
-                        let request: PreviewRequestPayload = decode_payload(&message.payload)
-                            .map_err(|e| {
-                                OpsError::invalid_operation(format!("decode error: {}", e))
-                            })?;
-                        debug!("Received preview request for hash: {}", request.hash);
-                        let response = self.handle_preview_request(&nodalync_peer, &request)?;
-                        let response_bytes =
-                            nodalync_wire::encode_payload(&response).map_err(|e| {
-                                OpsError::invalid_operation(format!("encoding error: {}", e))
-                            })?;
-                        Ok(Some((MessageType::PreviewResponse, response_bytes)))
-                    }
-                    MessageType::QueryRequest => {
-                        let request: QueryRequestPayload = decode_payload(&message.payload)
-                            .map_err(|e| {
-                                OpsError::invalid_operation(format!("decode error: {}", e))
-                            })?;
-                        debug!("Received query request for hash: {}", request.hash);
-
-                        // Handle query request and convert errors to QueryError responses
-                        match self.handle_query_request(&nodalync_peer, &request).await {
-                            Ok(response) => {
-                                let response_bytes = nodalync_wire::encode_payload(&response)
-                                    .map_err(|e| {
-                                        OpsError::invalid_operation(format!(
-                                            "encoding error: {}",
-                                            e
-                                        ))
-                                    })?;
-                                Ok(Some((MessageType::QueryResponse, response_bytes)))
+                // Owners' tombstones share the announce topic
+                if message.message_type == MessageType::Tombstone {
+                    match decode_payload::<TombstonePayload>(&message.payload) {
+                        Ok(payload) => {
+                            if let Err(e) = self.handle_tombstone(&payload) {
+                                warn!(hash = %payload.tombstone.content_hash, error = %e, "Ignoring invalid tombstone");
                             }
-                            Err(OpsError::ChannelRequired) => {
-                                // Return QueryError with our peer IDs so client can open channel
-                                use nodalync_wire::QueryErrorPayload;
-                                let error_payload = QueryErrorPayload {
-                                    hash: request.hash,
-                                    error_code: nodalync_types::ErrorCode::ChannelNotFound,
-                                    message: Some(
-                                        "Payment channel required for paid content".to_string(),
-                                    ),
-                                    required_channel_peer_id: Some(self.peer_id()),
-                                    required_channel_libp2p_peer: self
-                                        .network()
-                                        .map(|n| n.local_peer_id().to_string()),
-                                };
-                                info!(
-                                    requester = %nodalync_peer,
-                                    our_peer_id = %self.peer_id(),
-                                    "Returning ChannelRequired error with peer info"
-                                );
-                                let error_bytes = nodalync_wire::encode_payload(&error_payload)
-                                    .map_err(|e| {
-                                        OpsError::invalid_operation(format!(
-                                            "encoding error: {}",
-                                            e
-                                        ))
-                                    })?;
-                                Ok(Some((MessageType::QueryError, error_bytes)))
+                        }
+                        Err(e) => debug!("Failed to decode tombstone payload: {}", e),
+                    }
+                    return Ok(());
+                }
+
+                // So do content reports
+                if message.message_type == MessageType::Report {
+                    match decode_payload::<ReportPayload>(&message.payload) {
+                        Ok(payload) => {
+                            if let Err(e) = self.handle_report(&payload) {
+                                warn!(hash = %payload.report.content_hash, error = %e, "Ignoring invalid report");
                             }
-                            Err(e) => {
-                                // For other errors, return QueryError without peer info
-                                use nodalync_wire::QueryErrorPayload;
-                                let error_payload = QueryErrorPayload {
-                                    hash: request.hash,
-                                    error_code: e.error_code(),
-                                    message: Some(e.to_string()),
-                                    required_channel_peer_id: None,
-                                    required_channel_libp2p_peer: None,
-                                };
-                                let error_bytes = nodalync_wire::encode_payload(&error_payload)
-                                    .map_err(|e| {
-                                        OpsError::invalid_operation(format!(
-                                            "encoding error: {}",
-                                            e
-                                        ))
-                                    })?;
-                                Ok(Some((MessageType::QueryError, error_bytes)))
+                        }
+                        Err(e) => debug!("Failed to decode report payload: {}", e),
+                    }
+                    return Ok(());
+                }
+
+                // And fraud proofs against providers
+                if message.message_type == MessageType::FraudProof {
+                    match decode_payload::<FraudProofPayload>(&message.payload) {
+                        Ok(payload) => {
+                            if let Err(e) = self.handle_fraud_proof(&payload).await {
+                                warn!(provider = %payload.proof.provider, error = %e, "Ignoring invalid fraud proof");
                             }
                         }
+                        Err(e) => debug!("Failed to decode fraud proof payload: {}", e),
                     }
-                    MessageType::VersionRequest => {
-                        let request: VersionRequestPayload = decode_payload(&message.payload)
-                            .map_err(|e| {
-                                OpsError::invalid_operation(format!("decode error: {}", e))
-                            })?;
-                        debug!(
-                            "Received version request for root: {}",
-                            request.version_root
+                    return Ok(());
+                }
+
+                // Check if this is an ANNOUNCE message
+                if message.message_type != MessageType::Announce {
+                    debug!(
+                        "Ignoring non-announce broadcast message: {:?}",
+                        message.message_type
+                    );
+                    return Ok(());
+                }
+
+                // Decode the AnnouncePayload from the message payload
+                match decode_payload::<AnnouncePayload>(&message.payload) {
+                    Ok(payload) => {
+                        info!(
+                            hash = %payload.hash,
+                            title = %payload.title,
+                            price = payload.price,
+                            addresses = ?payload.addresses,
+                            "Received content announcement"
                         );
-                        let response = self.handle_version_request(&nodalync_peer, &request)?;
+
+                        // Filter out spam, then store the announcement in our cache
+                        // for later lookup. This allows preview/query to find
+                        // content from remote nodes
+                        self.filter_and_store_announcement(&message.sender, payload);
+                        Ok(())
+                    }
+                    Err(e) => {
+                        debug!("Failed to decode announcement payload: {}", e);
+                        Ok(()) // Don't fail on decode errors
+                    }
+                }
+            }
+            Err(e) => {
+                debug!("Failed to decode broadcast message: {}", e);
+                Ok(()) // Don't fail on decode errors
+            }
+        }
+    }
+
+    /// Store an announcement if its publisher signature is valid.
+    ///
+    /// Unsigned announcements are stored unverified. Content withdrawn by
+    /// its publisher, or published by a revoked key, is not stored.
+    /// Returns whether the announcement was stored.
+    pub(crate) fn store_verified_announcement(&self, payload: AnnouncePayload) -> bool {
+        if !self.announcement_acceptable(&payload) {
+            return false;
+        }
+        self.state.store_announcement(payload);
+        true
+    }
+
+    /// Check an announcement's signature and that neither its publisher key,
+    /// the replica announcing it, nor the content has been revoked or
+    /// withdrawn.
+    pub(crate) fn announcement_acceptable(&self, payload: &AnnouncePayload) -> bool {
+        match self.validator.validate_announcement(payload) {
+            Ok(Some(publisher)) if self.is_peer_revoked(&publisher) => {
+                debug!(hash = %payload.hash, publisher = %publisher, "Dropping announcement from revoked key");
+                false
+            }
+            Ok(_)
+                if payload
+                    .delegation
+                    .as_ref()
+                    .is_some_and(|d| self.is_peer_revoked(&d.replica)) =>
+            {
+                debug!(hash = %payload.hash, "Dropping announcement from revoked replica");
+                false
+            }
+            Ok(publisher) if self.is_withdrawn_by(&payload.hash, publisher.as_ref()) => {
+                debug!(hash = %payload.hash, "Dropping announcement of withdrawn content");
+                false
+            }
+            Ok(publisher) => {
+                debug!(hash = %payload.hash, publisher = ?publisher, "Announcement accepted");
+                true
+            }
+            Err(e) => {
+                warn!(hash = %payload.hash, error = %e, "Dropping announcement with invalid signature");
+                false
+            }
+        }
+    }
+
+    /// Handle an inbound request-response message.
+    ///
+    /// `data` is the encoded wire message received from `peer`. Verifies the
+    /// sender's signature when its key is known, dispatches on the message
+    /// type, and returns the response (MessageType, serialized_payload), if
+    /// any, for the caller to sign and send back.
+    pub async fn handle_inbound_request(
+        &self,
+        peer: &nodalync_net::PeerId,
+        data: &[u8],
+    ) -> OpsResult<Option<(MessageType, Vec<u8>)>> {
+        // First decode the full wire message (header + payload + signature)
+        let message = match decode_message(data) {
+            Ok(msg) => msg,
+            Err(e) => {
+                debug!(
+                    "Failed to decode message: {}, data length: {}",
+                    e,
+                    data.len()
+                );
+                return Ok(None);
+            }
+        };
+
+        debug!(
+            "Received message type {:?} from peer {}, sender {:?}, payload length: {}",
+            message.message_type,
+            peer,
+            message.sender,
+            message.payload.len()
+        );
+
+        // SECURITY: Verify message signature before trusting sender identity.
+        let sender_pubkey = self
+            .state
+            .peers
+            .get(&message.sender)
+            .ok()
+            .flatten()
+            .map(|info| info.public_key)
+            .filter(|pk| pk.0 != [0u8; 32]);
+
+        if let Some(pubkey) = &sender_pubkey {
+            if !nodalync_wire::verify_message_signature(&message, pubkey) {
+                tracing::warn!(
+                    sender = %message.sender,
+                    msg_type = ?message.message_type,
+                    "Message signature verification FAILED - rejecting"
+                );
+                return Ok(None);
+            }
+        } else {
+            // Peer key not yet known — soft-fail during bootstrap.
+            tracing::debug!(
+                sender = %message.sender,
+                "No public key for sender - skipping signature verification"
+            );
+        }
+
+        // Nothing from a revoked key is accepted, whatever it signs
+        if let Err(e) =
+            validate_message_not_revoked(&message, &StoredRevocations(&self.state.revocations))
+        {
+            warn!(sender = %message.sender, msg_type = ?message.message_type, "Rejecting message: {}", e);
+            return Ok(None);
+        }
+
+        // Timestamps are checked against our clock corrected for skew. Pings
+        // are answered whatever theirs, so a peer with a skewed clock can
+        // find out how far off it is.
+        if message.message_type != MessageType::Ping {
+            if let Err(e) = validate_message_basic(&message, self.network_time()) {
+                warn!(
+                    sender = %message.sender,
+                    msg_type = ?message.message_type,
+                    clock_offset_ms = self.clock_offset(),
+                    "Rejecting message: {}",
+                    e
+                );
+                return Ok(None);
+            }
+        }
+
+        let nodalync_peer = message.sender;
+
+        // Handle the request based on message type
+        match message.message_type {
+            MessageType::Ping => {
+                let ping: PingPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                // Our unadjusted clock, so our own correction doesn't feed
+                // into the pinger's estimate
+                let pong = PongPayload {
+                    nonce: ping.nonce,
+                    timestamp: current_timestamp(),
+                };
+                let response_bytes = nodalync_wire::encode_payload(&pong)
+                    .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?;
+                Ok(Some((MessageType::Pong, response_bytes)))
+            }
+            MessageType::PreviewRequest => {
+                let request: PreviewRequestPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!("Received preview request for hash: {}", request.hash);
+                let response = self.handle_preview_request(&nodalync_peer, &request)?;
+                let response_bytes = nodalync_wire::encode_payload(&response)
+                    .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?;
+                Ok(Some((MessageType::PreviewResponse, response_bytes)))
+            }
+            MessageType::QueryRequest => {
+                let request: QueryRequestPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!("Received query request for hash: {}", request.hash);
+
+                // Handle query request and convert errors to QueryError responses
+                match self.handle_query_request(&nodalync_peer, &request).await {
+                    Ok(response) => {
                         let response_bytes =
                             nodalync_wire::encode_payload(&response).map_err(|e| {
                                 OpsError::invalid_operation(format!("encoding error: {}", e))
                             })?;
-                        Ok(Some((MessageType::VersionResponse, response_bytes)))
+                        Ok(Some((MessageType::QueryResponse, response_bytes)))
                     }
-                    MessageType::ChannelOpen => {
-                        let request: ChannelOpenPayload = decode_payload(&message.payload)
-                            .map_err(|e| {
-                                OpsError::invalid_operation(format!("decode error: {}", e))
+                    Err(e) => {
+                        // Our peer IDs when a channel is required, the
+                        // challenge if one was issued, and retry hints
+                        let error_payload = self.query_error_payload(request.hash, peer, &e);
+                        debug!(
+                            requester = %nodalync_peer,
+                            hash = %request.hash,
+                            code = ?error_payload.error_code,
+                            "Refusing query: {}",
+                            e
+                        );
+                        let error_bytes =
+                            nodalync_wire::encode_payload(&error_payload).map_err(|e| {
+                                OpsError::invalid_operation(format!("encoding error: {}", e))
                             })?;
-                        debug!("Received channel open request");
-                        let response = self.handle_channel_open(&nodalync_peer, &request).await?;
+                        Ok(Some((MessageType::QueryError, error_bytes)))
+                    }
+                }
+            }
+            MessageType::VersionRequest => {
+                let request: VersionRequestPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!(
+                    "Received version request for root: {}",
+                    request.version_root
+                );
+                let response = self.handle_version_request(&nodalync_peer, &request)?;
+                let response_bytes = nodalync_wire::encode_payload(&response)
+                    .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?;
+                Ok(Some((MessageType::VersionResponse, response_bytes)))
+            }
+            MessageType::ChannelOpen => {
+                let request: ChannelOpenPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!("Received channel open request");
+                let response = self.handle_channel_open(&nodalync_peer, &request).await?;
+                let response_bytes = nodalync_wire::encode_payload(&response)
+                    .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?;
+                Ok(Some((MessageType::ChannelAccept, response_bytes)))
+            }
+            MessageType::ChannelClose => {
+                let request: ChannelClosePayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!("Received channel close request");
+
+                match self.private_key().cloned() {
+                    Some(pk) => {
+                        let ack =
+                            self.handle_channel_close_request(&nodalync_peer, &request, &pk)?;
+                        let response_bytes = nodalync_wire::encode_payload(&ack).map_err(|e| {
+                            OpsError::invalid_operation(format!("encoding error: {}", e))
+                        })?;
+                        Ok(Some((MessageType::ChannelCloseAck, response_bytes)))
+                    }
+                    None => Err(OpsError::invalid_operation(
+                        "private key required for channel close",
+                    )),
+                }
+            }
+            MessageType::ChannelCloseAck => {
+                // This is handled by the initiator when they receive the response
+                // No action needed here as it's processed in close_payment_channel()
+                debug!("Received channel close ack (handled by initiator)");
+                Ok(None)
+            }
+            MessageType::ChannelSync => {
+                let request: ChannelSyncPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!("Received channel sync request");
+                let response = self.handle_channel_sync(&nodalync_peer, &request)?;
+                let response_bytes = nodalync_wire::encode_payload(&response)
+                    .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?;
+                Ok(Some((MessageType::ChannelSyncResponse, response_bytes)))
+            }
+            MessageType::ChannelAccept => {
+                let response: ChannelAcceptPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!("Received channel accept response");
+                self.handle_channel_accept(&nodalync_peer, &response)?;
+                Ok(None) // No response needed for accept
+            }
+            MessageType::Search => {
+                let request: SearchPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!("Received search request for query: {}", request.query);
+                let response = self.handle_search_request(&nodalync_peer, &request)?;
+                let response_bytes = nodalync_wire::encode_payload(&response)
+                    .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?;
+                Ok(Some((MessageType::SearchResponse, response_bytes)))
+            }
+            MessageType::Invoice => {
+                let request: InvoicePayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!("Received invoice {}", request.invoice.hash);
+                let ack = self
+                    .handle_invoice(&nodalync_peer, &request)
+                    .unwrap_or_else(|e| invoice_rejection(request.invoice.hash, &e));
+                let response_bytes = nodalync_wire::encode_payload(&ack)
+                    .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?;
+                Ok(Some((MessageType::InvoiceAck, response_bytes)))
+            }
+            MessageType::InvoiceRequest => {
+                let request: InvoiceRequestPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!("Received invoice request for {}", request.invoice_hash);
+                let (message_type, response_bytes) =
+                    match self.handle_invoice_request(&nodalync_peer, &request)? {
+                        Some(response) => (
+                            MessageType::Invoice,
+                            nodalync_wire::encode_payload(&response),
+                        ),
+                        None => (
+                            MessageType::InvoiceAck,
+                            nodalync_wire::encode_payload(&invoice_rejection(
+                                request.invoice_hash,
+                                &OpsError::InvoiceNotFound(request.invoice_hash),
+                            )),
+                        ),
+                    };
+                let response_bytes = response_bytes
+                    .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?;
+                Ok(Some((message_type, response_bytes)))
+            }
+            MessageType::GroupRequest => {
+                let request: GroupRequestPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!("Received group request for {}", request.group_id);
+                let response = self.handle_group_request(&nodalync_peer, &request)?;
+                let response_bytes = nodalync_wire::encode_payload(&response)
+                    .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?;
+                Ok(Some((MessageType::GroupResponse, response_bytes)))
+            }
+            MessageType::SnapshotRequest => {
+                let request: SnapshotRequestPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!("Received snapshot request");
+                let response = self.handle_snapshot_request(&nodalync_peer, &request)?;
+                let response_bytes = nodalync_wire::encode_payload(&response)
+                    .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?;
+                Ok(Some((MessageType::SnapshotResponse, response_bytes)))
+            }
+            MessageType::SyncPush => {
+                let request: SyncPushPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!("Received sync bundle from {}", request.bundle.owner);
+                let ack = self.handle_sync_push(&nodalync_peer, &request)?;
+                let response_bytes = nodalync_wire::encode_payload(&ack)
+                    .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?;
+                Ok(Some((MessageType::SyncPushAck, response_bytes)))
+            }
+            MessageType::SyncPull => {
+                let request: SyncPullPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!("Received sync pull for {}", request.owner);
+                let response = self.handle_sync_pull(&nodalync_peer, &request)?;
+                let response_bytes = nodalync_wire::encode_payload(&response)
+                    .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?;
+                Ok(Some((MessageType::SyncPullResponse, response_bytes)))
+            }
+            MessageType::AvailabilityCheck => {
+                let request: AvailabilityCheckPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!("Received availability check for {}", request.hash);
+                let result = self
+                    .handle_availability_check(peer, &nodalync_peer, &request)
+                    .await;
+                let response_bytes = nodalync_wire::encode_payload(&result)
+                    .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?;
+                Ok(Some((MessageType::AvailabilityResult, response_bytes)))
+            }
+            MessageType::InvoicePay => {
+                let request: InvoicePayPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!("Received payment for invoice {}", request.invoice_hash);
+                let ack = self
+                    .handle_invoice_payment(&nodalync_peer, &request)
+                    .unwrap_or_else(|e| invoice_rejection(request.invoice_hash, &e));
+                let response_bytes = nodalync_wire::encode_payload(&ack)
+                    .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?;
+                Ok(Some((MessageType::InvoiceAck, response_bytes)))
+            }
+            MessageType::ChunkRequest => {
+                let request: ChunkRequestPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!("Received chunk request for {}", request.hash);
+
+                match self.handle_chunk_request(&nodalync_peer, &request).await {
+                    Ok(response) => {
                         let response_bytes =
                             nodalync_wire::encode_payload(&response).map_err(|e| {
                                 OpsError::invalid_operation(format!("encoding error: {}", e))
                             })?;
-                        Ok(Some((MessageType::ChannelAccept, response_bytes)))
+                        Ok(Some((MessageType::ChunkResponse, response_bytes)))
                     }
-                    MessageType::ChannelClose => {
-                        let request: ChannelClosePayload = decode_payload(&message.payload)
-                            .map_err(|e| {
-                                OpsError::invalid_operation(format!("decode error: {}", e))
-                            })?;
-                        debug!("Received channel close request");
-
-                        match self.private_key().cloned() {
-                            Some(pk) => {
-                                let ack = self.handle_channel_close_request(
-                                    &nodalync_peer,
-                                    &request,
-                                    &pk,
-                                )?;
-                                let response_bytes =
-                                    nodalync_wire::encode_payload(&ack).map_err(|e| {
-                                        OpsError::invalid_operation(format!(
-                                            "encoding error: {}",
-                                            e
-                                        ))
-                                    })?;
-                                Ok(Some((MessageType::ChannelCloseAck, response_bytes)))
-                            }
-                            None => Err(OpsError::invalid_operation(
-                                "private key required for channel close",
-                            )),
-                        }
-                    }
-                    MessageType::ChannelCloseAck => {
-                        // This is handled by the initiator when they receive the response
-                        // No action needed here as it's processed in close_payment_channel()
-                        debug!("Received channel close ack (handled by initiator)");
-                        Ok(None)
-                    }
-                    MessageType::ChannelAccept => {
-                        let response: ChannelAcceptPayload = decode_payload(&message.payload)
-                            .map_err(|e| {
-                                OpsError::invalid_operation(format!("decode error: {}", e))
+                    Err(e) => {
+                        let error_payload = self.query_error_payload(request.hash, peer, &e);
+                        let error_bytes =
+                            nodalync_wire::encode_payload(&error_payload).map_err(|e| {
+                                OpsError::invalid_operation(format!("encoding error: {}", e))
                             })?;
-                        debug!("Received channel accept response");
-                        self.handle_channel_accept(&nodalync_peer, &response)?;
-                        Ok(None) // No response needed for accept
+                        Ok(Some((MessageType::QueryError, error_bytes)))
                     }
-                    MessageType::Search => {
-                        let request: SearchPayload =
-                            decode_payload(&message.payload).map_err(|e| {
-                                OpsError::invalid_operation(format!("decode error: {}", e))
-                            })?;
-                        debug!("Received search request for query: {}", request.query);
-                        let response = self.handle_search_request(&nodalync_peer, &request)?;
+                }
+            }
+            MessageType::RelayQuery => {
+                let request: RelayQueryPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!("Received relayed query");
+
+                // Errors go back as a QueryError, which only the previous
+                // hop sees
+                match self.handle_relay_query(&nodalync_peer, &request).await {
+                    Ok(response) => {
                         let response_bytes =
                             nodalync_wire::encode_payload(&response).map_err(|e| {
                                 OpsError::invalid_operation(format!("encoding error: {}", e))
                             })?;
-                        Ok(Some((MessageType::SearchResponse, response_bytes)))
+                        Ok(Some((MessageType::RelayResponse, response_bytes)))
                     }
-                    _ => {
-                        debug!("Unhandled message type: {:?}", message.message_type);
-                        Ok(None)
+                    Err(e) => {
+                        let error_payload = self.query_error_payload(
+                            content_hash(&request.layer.ciphertext),
+                            peer,
+                            &e,
+                        );
+                        let error_bytes =
+                            nodalync_wire::encode_payload(&error_payload).map_err(|e| {
+                                OpsError::invalid_operation(format!("encoding error: {}", e))
+                            })?;
+                        Ok(Some((MessageType::QueryError, error_bytes)))
                     }
                 }
             }
+            MessageType::PeerInfo => {
+                let info: PeerInfoPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                if info.peer_id != nodalync_peer {
+                    warn!(sender = %nodalync_peer, "Rejecting peer info for another peer");
+                    return Ok(None);
+                }
+                // A first contact's message must be signed by the key it
+                // advertises
+                if sender_pubkey.is_none()
+                    && !nodalync_wire::verify_message_signature(&message, &info.public_key)
+                {
+                    warn!(sender = %nodalync_peer, "Rejecting unsigned peer info");
+                    return Ok(None);
+                }
+                debug!(peer = %info.peer_id, capabilities = ?info.capabilities, "Received peer info");
+                self.handle_peer_info(&info)?;
+                let response_bytes = nodalync_wire::encode_payload(&self.local_peer_info()?)
+                    .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?;
+                Ok(Some((MessageType::PeerInfo, response_bytes)))
+            }
+            MessageType::UsageReport => {
+                let report: UsageReportPayload = decode_payload(&message.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                debug!("Received usage report for {}", report.hash);
+                let ack = self.handle_usage_report(&nodalync_peer, &report);
+                let response_bytes = nodalync_wire::encode_payload(&ack)
+                    .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?;
+                Ok(Some((MessageType::UsageReportAck, response_bytes)))
+            }
+            _ => {
+                debug!("Unhandled message type: {:?}", message.message_type);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Handle an incoming network event.
+    ///
+    /// This is the main entry point for processing network events and
+    /// dispatching to the appropriate handler. Returns an optional response
+    /// that should be encoded and sent back to the peer.
+    ///
+    /// The response is returned as (MessageType, serialized_payload) for the
+    /// caller to construct the actual Message envelope.
+    pub async fn handle_network_event(
+        &self,
+        event: NetworkEvent,
+    ) -> OpsResult<Option<(MessageType, Vec<u8>)>> {
+        match event {
+            NetworkEvent::InboundRequest { peer, data, .. } => {
+                self.handle_inbound_request(&peer, &data).await
+            }
             NetworkEvent::PeerConnected { peer } => {
                 // Log peer connection (could track connected peers in state)
                 let _ = peer;
@@ -1174,7 +2155,7 @@ where
             }
             NetworkEvent::BroadcastReceived { topic, data } => {
                 // Handle content announcements from GossipSub
-                self.handle_broadcast_announcement(&topic, &data)?;
+                self.handle_broadcast_announcement(&topic, &data).await?;
                 Ok(None)
             }
             _ => {
@@ -1185,6 +2166,34 @@ where
     }
 }
 
+/// An ack rejecting an invoice or invoice payment because of `error`.
+fn usage_report_ack(hash: Hash, result: OpsResult<()>) -> UsageReportAckPayload {
+    match result {
+        Ok(()) => UsageReportAckPayload {
+            hash,
+            accepted: true,
+            reason: None,
+        },
+        Err(e) => {
+            debug!(hash = %hash, error = %e, "Rejecting usage report");
+            UsageReportAckPayload {
+                hash,
+                accepted: false,
+                reason: Some(e.to_string()),
+            }
+        }
+    }
+}
+
+fn invoice_rejection(invoice_hash: Hash, error: &OpsError) -> InvoiceAckPayload {
+    warn!(hash = %invoice_hash, error = %error, "Rejecting invoice message");
+    InvoiceAckPayload {
+        invoice_hash,
+        accepted: false,
+        reason: Some(error.to_string()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1192,7 +2201,7 @@ mod tests {
     use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
     use nodalync_store::NodeStateConfig;
     use nodalync_types::{Metadata, ProvenanceEntry};
-    use nodalync_wire::ChannelBalances;
+    use nodalync_wire::{ChannelBalances, SearchFilters};
     use std::sync::Arc;
     use tempfile::TempDir;
 
@@ -1208,6 +2217,20 @@ mod tests {
         (ops, temp_dir)
     }
 
+    /// Test ops holding the private key for their peer ID.
+    fn create_keyed_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let config = NodeStateConfig::new(temp_dir.path());
+        let state = nodalync_store::NodeState::open(config).unwrap();
+
+        let (private_key, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+
+        let mut ops = DefaultNodeOperations::with_defaults(state, peer_id);
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
     fn test_peer_id() -> PeerId {
         let (_, public_key) = generate_identity();
         peer_id_from_public_key(&public_key)
@@ -1248,7 +2271,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_handle_preview_request() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create and publish content
         let content = b"Test content for preview";
@@ -1266,11 +2289,15 @@ mod tests {
 
         assert_eq!(response.hash, hash);
         assert_eq!(response.manifest.economics.price, 100);
+
+        let stats = ops.content_stats(&hash).unwrap();
+        assert_eq!(stats.previews, 1);
+        assert_eq!(stats.unique_consumers, 1);
     }
 
     #[test]
     fn test_handle_preview_request_private() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create private content
         let content = b"Private content";
@@ -1288,7 +2315,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_handle_query_request() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create and publish content
         let content = b"Test content for query";
@@ -1318,9 +2345,17 @@ mod tests {
         let request = QueryRequestPayload {
             hash,
             query: None,
-            payment,
+            payment: Some(payment),
             version_spec: None,
             payment_nonce: 1,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
         };
 
         // Paid content queries require on-chain settlement to be configured.
@@ -1335,7 +2370,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_handle_query_request_insufficient_payment() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create and publish content with price
         let content = b"Paid content";
@@ -1359,18 +2394,259 @@ mod tests {
         let request = QueryRequestPayload {
             hash,
             query: None,
-            payment,
+            payment: Some(payment),
             version_spec: None,
             payment_nonce: 1,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        };
+
+        let result = ops.handle_query_request(&requester, &request).await;
+        assert!(matches!(result, Err(OpsError::PaymentInsufficient)));
+    }
+
+    #[tokio::test]
+    async fn test_handle_query_request_below_dynamic_price() {
+        use nodalync_types::PricingRule;
+
+        let (ops, _temp) = create_test_ops();
+
+        let content = b"Fresh data";
+        let meta = Metadata::new("Fresh", content.len() as u64);
+        let hash = ops.create_content(content, meta).unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 1000)
+            .await
+            .unwrap();
+
+        // A Dutch auction that just started charges about the start price
+        let rule = PricingRule::Decay {
+            start_price: 5000,
+            starts_at: current_timestamp(),
+            duration_ms: 3_600_000,
         };
+        ops.set_content_pricing(&hash, Some(rule)).unwrap();
 
+        let requester = test_peer_id();
+        let channel_id = content_hash(b"test-dynamic-channel");
+        ops.accept_payment_channel(&channel_id, &requester, 10_000, 1000)
+            .unwrap();
+
+        // The base price is no longer enough
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        let request = QueryRequestPayload {
+            hash,
+            query: None,
+            payment: Some(create_test_payment(1000, manifest.owner, hash)),
+            version_spec: None,
+            payment_nonce: 1,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        };
         let result = ops.handle_query_request(&requester, &request).await;
         assert!(matches!(result, Err(OpsError::PaymentInsufficient)));
+
+        // Previews carry the rule; decay needs no window count
+        let preview = ops
+            .handle_preview_request(&requester, &PreviewRequestPayload { hash })
+            .unwrap();
+        assert_eq!(preview.manifest.economics.pricing, Some(rule));
+        assert_eq!(preview.window_queries, None);
+    }
+
+    #[tokio::test]
+    async fn test_handle_query_request_for_section() {
+        use nodalync_test_utils::MockSettlement;
+
+        let temp_dir = TempDir::new().unwrap();
+        let state = nodalync_store::NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let mock_settle = Arc::new(MockSettlement::new());
+        let ops = DefaultNodeOperations::with_config_and_settlement(
+            state,
+            test_peer_id(),
+            crate::config::OpsConfig::default(),
+            mock_settle.clone(),
+        );
+
+        let content = b"# Summary\nShort\n# Data\nRows and rows\n";
+        let hash = ops
+            .create_content(content, Metadata::new("Report", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 1_000)
+            .await
+            .unwrap();
+        ops.set_section_prices(
+            &hash,
+            &[("Summary".to_string(), 0), ("Data".to_string(), 200)],
+        )
+        .unwrap();
+
+        let requester = test_peer_id();
+        let channel_id = content_hash(b"section-channel");
+        ops.accept_payment_channel(&channel_id, &requester, 5_000, 1_000)
+            .unwrap();
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        let request = |section, amount| QueryRequestPayload {
+            hash,
+            query: None,
+            payment: (amount > 0).then(|| {
+                create_test_payment_with_provenance(
+                    amount,
+                    manifest.owner,
+                    hash,
+                    channel_id,
+                    manifest.provenance.root_l0l1.clone(),
+                )
+            }),
+            version_spec: None,
+            payment_nonce: 1,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        };
+
+        // A free section needs no payment
+        let response = ops
+            .handle_query_request(&requester, &request(Some(0), 0))
+            .await
+            .unwrap();
+        assert_eq!(response.content, b"# Summary\nShort\n".to_vec());
+        assert_eq!(response.section, Some(0));
+        assert!(response.delivery.is_none());
+
+        // A paid section costs its own price, not the document's
+        let response = ops
+            .handle_query_request(&requester, &request(Some(1), 200))
+            .await
+            .unwrap();
+        assert_eq!(response.content, b"# Data\nRows and rows\n".to_vec());
+        assert_eq!(response.payment_receipt.amount, 200);
+        assert!(manifest.metadata.sections[1].verify(&response.content));
+
+        // Revenue is distributed from the section price
+        let batches = mock_settle.settled_batches();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].total_amount(), 200);
+
+        // The whole document still costs its full price
+        let result = ops
+            .handle_query_request(&requester, &request(None, 200))
+            .await;
+        assert!(matches!(result, Err(OpsError::PaymentInsufficient)));
+
+        let result = ops
+            .handle_query_request(&requester, &request(Some(2), 200))
+            .await;
+        assert!(matches!(
+            result,
+            Err(OpsError::SectionNotFound { index: 2, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_handle_query_request_for_metered_unit() {
+        use nodalync_test_utils::MockSettlement;
+
+        let temp_dir = TempDir::new().unwrap();
+        let state = nodalync_store::NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let mock_settle = Arc::new(MockSettlement::new());
+        let ops = DefaultNodeOperations::with_config_and_settlement(
+            state,
+            test_peer_id(),
+            crate::config::OpsConfig::default(),
+            mock_settle.clone(),
+        );
+
+        let content: Vec<u8> = (0..3_000u32).map(|i| (i % 251) as u8).collect();
+        let hash = ops
+            .create_content(&content, Metadata::new("Log", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 900)
+            .await
+            .unwrap();
+        ops.set_content_metering(&hash, Some(1_024)).unwrap();
+
+        let requester = test_peer_id();
+        let channel_id = content_hash(b"metered-channel");
+        ops.accept_payment_channel(&channel_id, &requester, 5_000, 1_000)
+            .unwrap();
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        let units = manifest.metadata.metering.clone().unwrap();
+        let request = |unit, amount, nonce| QueryRequestPayload {
+            hash,
+            query: None,
+            payment: Some(create_test_payment_with_provenance(
+                amount,
+                manifest.owner,
+                hash,
+                channel_id,
+                manifest.provenance.root_l0l1.clone(),
+            )),
+            version_spec: None,
+            payment_nonce: nonce,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit,
+            referrer: None,
+            entitlement: None,
+        };
+
+        // Each unit costs its share of the price and is served on its own
+        for index in 0..2u32 {
+            let response = ops
+                .handle_query_request(&requester, &request(Some(index), 300, u64::from(index) + 1))
+                .await
+                .unwrap();
+            assert_eq!(response.unit, Some(index));
+            assert_eq!(response.payment_receipt.amount, 300);
+            assert!(units.verify_chunk(index, &response.content));
+            assert!(response.delivery.is_none());
+        }
+        assert_eq!(mock_settle.settled_batches().len(), 2);
+
+        // The read counts as one query, earning what was paid
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        assert_eq!(manifest.economics.total_queries, 1);
+        assert_eq!(manifest.economics.total_revenue, 600);
+
+        let result = ops
+            .handle_query_request(&requester, &request(Some(2), 200, 3))
+            .await;
+        assert!(matches!(result, Err(OpsError::PaymentInsufficient)));
+        let result = ops
+            .handle_query_request(&requester, &request(Some(3), 300, 3))
+            .await;
+        assert!(matches!(
+            result,
+            Err(OpsError::UnitNotFound { index: 3, .. })
+        ));
+        let mut both = request(Some(2), 300, 3);
+        both.section = Some(0);
+        assert!(ops.handle_query_request(&requester, &both).await.is_err());
     }
 
     #[test]
     fn test_handle_version_request() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create content with versions
         let content1 = b"Version 1";
@@ -1385,6 +2661,8 @@ mod tests {
         let requester = test_peer_id();
         let request = VersionRequestPayload {
             version_root: hash1,
+            delta_from: None,
+            include_manifests: false,
         };
 
         let response = ops.handle_version_request(&requester, &request).unwrap();
@@ -1393,11 +2671,65 @@ mod tests {
         assert!(!response.versions.is_empty());
         // latest is a Hash, not Option
         assert!(response.latest.0.iter().any(|&b| b != 0));
+        assert!(response.delta.is_none());
+    }
+
+    #[test]
+    fn test_handle_version_request_delta() {
+        let (ops, _temp) = create_test_ops();
+
+        let content1 = b"Version 1 of a shared document".repeat(20);
+        let meta1 = Metadata::new("v1", content1.len() as u64);
+        let hash1 = ops
+            .create_content_with_timestamp(&content1, meta1, 1000)
+            .unwrap();
+
+        let mut content2 = content1.clone();
+        content2.extend_from_slice(b" with an edit");
+        let meta2 = Metadata::new("v2", content2.len() as u64);
+        let hash2 = ops
+            .update_content_with_timestamp(&hash1, &content2, meta2, 2000)
+            .unwrap();
+
+        let requester = test_peer_id();
+        let request = VersionRequestPayload {
+            version_root: hash1,
+            delta_from: Some(hash1),
+            include_manifests: false,
+        };
+
+        // Private versions get no delta
+        let response = ops.handle_version_request(&requester, &request).unwrap();
+        assert_eq!(response.latest, hash2);
+        assert!(response.delta.is_none());
+
+        // Free, shared versions do
+        let mut manifest2 = ops.state.manifests.load(&hash2).unwrap().unwrap();
+        manifest2.visibility = Visibility::Shared;
+        ops.state.manifests.update(&manifest2).unwrap();
+
+        let response = ops.handle_version_request(&requester, &request).unwrap();
+        let delta = response.delta.unwrap();
+        assert_eq!(delta.base, hash1);
+        assert_eq!(delta.manifest.hash, hash2);
+        let rebuilt = nodalync_store::delta::apply_delta(
+            &content1,
+            &delta.delta,
+            delta.manifest.metadata.content_size,
+        )
+        .unwrap();
+        assert_eq!(content_hash(&rebuilt), hash2);
+
+        // Paid versions must be queried instead
+        manifest2.economics.price = 100;
+        ops.state.manifests.update(&manifest2).unwrap();
+        let response = ops.handle_version_request(&requester, &request).unwrap();
+        assert!(response.delta.is_none());
     }
 
     #[tokio::test]
     async fn test_handle_channel_open() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         let requester = test_peer_id();
         let channel_id = content_hash(b"test channel");
@@ -1414,59 +2746,271 @@ mod tests {
 
         let response = ops.handle_channel_open(&requester, &request).await.unwrap();
 
-        assert_eq!(response.channel_id, channel_id);
-        assert_eq!(response.initial_balance, deposit);
+        assert_eq!(response.channel_id, channel_id);
+        assert_eq!(response.initial_balance, deposit);
+
+        // Verify channel was created
+        let channel = ops.get_payment_channel(&requester).unwrap().unwrap();
+        assert!(channel.is_open());
+    }
+
+    #[tokio::test]
+    async fn test_handle_channel_close() {
+        let (private_key, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = NodeStateConfig::new(temp_dir.path());
+        let state = nodalync_store::NodeState::open(config).unwrap();
+        let ops = DefaultNodeOperations::with_defaults(state, peer_id);
+
+        // First open a channel with deposit above minimum (100 HBAR)
+        let requester = test_peer_id();
+        let channel_id = content_hash(b"test channel");
+        let deposit = 200_0000_0000; // 200 HBAR in tinybars
+
+        let open_request = ChannelOpenPayload {
+            channel_id,
+            initial_balance: deposit,
+            funding_tx: None,
+            hedera_account: None,
+        };
+        ops.handle_channel_open(&requester, &open_request)
+            .await
+            .unwrap();
+
+        // Close it — requester is unknown peer, so soft-fail skips sig check
+        let close_request = ChannelClosePayload {
+            channel_id,
+            nonce: 0,
+            final_balances: ChannelBalances::new(deposit, deposit),
+            initiator_signature: Signature::from_bytes([0u8; 64]),
+        };
+
+        let _ack = ops
+            .handle_channel_close_request(&requester, &close_request, &private_key)
+            .unwrap();
+
+        // Verify channel is closing
+        let channel = ops.get_payment_channel(&requester).unwrap().unwrap();
+        assert!(channel.state == nodalync_types::ChannelState::Closing || channel.is_closed());
+    }
+
+    #[test]
+    fn test_handle_channel_sync_returns_signed_state() {
+        use nodalync_valid::verify_channel_close_signature;
+
+        let (private_key, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = NodeStateConfig::new(temp_dir.path());
+        let state = nodalync_store::NodeState::open(config).unwrap();
+        let mut ops = DefaultNodeOperations::with_defaults(state, peer_id);
+        ops.set_private_key(private_key);
+
+        let requester = test_peer_id();
+        let channel_id = content_hash(b"sync channel");
+        ops.accept_payment_channel(&channel_id, &requester, 500, 500)
+            .unwrap();
+
+        // Requester is ahead: they hold 400, we hold 600 at nonce 3
+        let request = ChannelSyncPayload {
+            channel_id,
+            nonce: 3,
+            balances: ChannelBalances::new(400, 600),
+            signature: Signature::from_bytes([0u8; 64]),
+        };
+        let response = ops.handle_channel_sync(&requester, &request).unwrap();
+
+        assert_eq!(response.nonce, 3);
+        assert_eq!(response.balances, ChannelBalances::new(600, 400));
+        assert!(verify_channel_close_signature(
+            &public_key,
+            &channel_id,
+            3,
+            600,
+            400,
+            &response.signature,
+        ));
+    }
+
+    #[test]
+    fn test_handle_invoice_payment() {
+        use crate::channel::create_signed_payment;
+        use nodalync_store::LedgerStore;
+
+        let (ops, _temp) = create_keyed_test_ops();
+        let invoice = ops.create_invoice(300, "Consulting", None, None).unwrap();
+
+        let (payer_key, payer_pubkey) = generate_identity();
+        let payer = peer_id_from_public_key(&payer_pubkey);
+        let channel_id = content_hash(b"invoice channel");
+        ops.accept_payment_channel(&channel_id, &payer, 500, 500)
+            .unwrap();
+        let channel = ops.state.channels.get(&payer).unwrap().unwrap();
+
+        let (payment, payment_nonce) = create_signed_payment(
+            &payer_key,
+            &channel,
+            300,
+            ops.peer_id(),
+            invoice.hash,
+            vec![],
+        );
+        let request = InvoicePayPayload {
+            invoice_hash: invoice.hash,
+            payment,
+            payment_nonce,
+        };
+
+        let ack = ops.handle_invoice_payment(&payer, &request).unwrap();
+        assert!(ack.accepted);
+
+        let record = ops.get_invoice(&invoice.hash).unwrap().unwrap();
+        assert_eq!(record.status, InvoiceStatus::Paid);
+        assert_eq!(record.payer, Some(payer));
+        let channel = ops.state.channels.get(&payer).unwrap().unwrap();
+        assert_eq!(channel.my_balance, 800);
+        assert_eq!(channel.their_balance, 200);
+        assert_eq!(
+            ops.state.ledger.balance(LedgerAccount::Revenue).unwrap(),
+            300
+        );
 
-        // Verify channel was created
-        let channel = ops.get_payment_channel(&requester).unwrap().unwrap();
-        assert!(channel.is_open());
+        // Paying the same invoice again is rejected
+        assert!(ops.handle_invoice_payment(&payer, &request).is_err());
     }
 
-    #[tokio::test]
-    async fn test_handle_channel_close() {
-        let (private_key, public_key) = generate_identity();
-        let peer_id = peer_id_from_public_key(&public_key);
-
-        let temp_dir = TempDir::new().unwrap();
-        let config = NodeStateConfig::new(temp_dir.path());
-        let state = nodalync_store::NodeState::open(config).unwrap();
-        let mut ops = DefaultNodeOperations::with_defaults(state, peer_id);
+    #[test]
+    fn test_handle_invoice_payment_rejects_wrong_amount() {
+        use crate::channel::create_signed_payment;
 
-        // First open a channel with deposit above minimum (100 HBAR)
-        let requester = test_peer_id();
-        let channel_id = content_hash(b"test channel");
-        let deposit = 200_0000_0000; // 200 HBAR in tinybars
+        let (ops, _temp) = create_keyed_test_ops();
+        let invoice = ops.create_invoice(300, "Consulting", None, None).unwrap();
 
-        let open_request = ChannelOpenPayload {
-            channel_id,
-            initial_balance: deposit,
-            funding_tx: None,
-            hedera_account: None,
-        };
-        ops.handle_channel_open(&requester, &open_request)
-            .await
+        let (payer_key, payer_pubkey) = generate_identity();
+        let payer = peer_id_from_public_key(&payer_pubkey);
+        ops.accept_payment_channel(&content_hash(b"invoice channel"), &payer, 500, 500)
             .unwrap();
+        let channel = ops.state.channels.get(&payer).unwrap().unwrap();
 
-        // Close it — requester is unknown peer, so soft-fail skips sig check
-        let close_request = ChannelClosePayload {
-            channel_id,
-            nonce: 0,
-            final_balances: ChannelBalances::new(deposit, deposit),
-            initiator_signature: Signature::from_bytes([0u8; 64]),
+        let (payment, payment_nonce) = create_signed_payment(
+            &payer_key,
+            &channel,
+            100,
+            ops.peer_id(),
+            invoice.hash,
+            vec![],
+        );
+        let request = InvoicePayPayload {
+            invoice_hash: invoice.hash,
+            payment,
+            payment_nonce,
         };
 
-        let _ack = ops
-            .handle_channel_close_request(&requester, &close_request, &private_key)
+        assert!(matches!(
+            ops.handle_invoice_payment(&payer, &request),
+            Err(OpsError::PaymentValidationFailed(_))
+        ));
+        assert_eq!(
+            ops.get_invoice(&invoice.hash).unwrap().unwrap().status,
+            InvoiceStatus::Unpaid
+        );
+    }
+
+    #[test]
+    fn test_handle_invoice_and_request() {
+        let (ops, _temp) = create_keyed_test_ops();
+        let issued = ops.create_invoice(300, "Consulting", None, None).unwrap();
+
+        // Issued invoices can be fetched by hash, unknown ones cannot
+        let request = InvoiceRequestPayload {
+            invoice_hash: issued.hash,
+        };
+        let response = ops
+            .handle_invoice_request(&test_peer_id(), &request)
+            .unwrap();
+        assert_eq!(response.unwrap().invoice, issued);
+        let unknown = InvoiceRequestPayload {
+            invoice_hash: content_hash(b"unknown"),
+        };
+        assert!(ops
+            .handle_invoice_request(&test_peer_id(), &unknown)
+            .unwrap()
+            .is_none());
+
+        // An invoice from another payee is tracked as received
+        let (payee_key, payee_pubkey) = generate_identity();
+        let now = current_timestamp();
+        let mut invoice = nodalync_types::Invoice::new(
+            peer_id_from_public_key(&payee_pubkey),
+            payee_pubkey,
+            250,
+            "Hosting",
+            None,
+            now,
+            now + 60_000,
+        );
+        nodalync_valid::sign_invoice(&payee_key, &mut invoice);
+        let ack = ops
+            .handle_invoice(
+                &invoice.payee,
+                &InvoicePayload {
+                    invoice: invoice.clone(),
+                },
+            )
             .unwrap();
+        assert!(ack.accepted);
+        assert_eq!(
+            ops.get_invoice(&invoice.hash).unwrap().unwrap().direction,
+            nodalync_store::InvoiceDirection::Received
+        );
 
-        // Verify channel is closing
-        let channel = ops.get_payment_channel(&requester).unwrap().unwrap();
-        assert!(channel.state == nodalync_types::ChannelState::Closing || channel.is_closed());
+        // Tampered invoices are refused
+        invoice.amount = 1;
+        let payee = invoice.payee;
+        assert!(ops
+            .handle_invoice(&payee, &InvoicePayload { invoice })
+            .is_err());
+    }
+
+    #[test]
+    fn test_handle_group_request() {
+        let (ops, _temp) = create_keyed_test_ops();
+        let group = ops.create_group("Team", vec![test_peer_id()]).unwrap();
+
+        let response = ops
+            .handle_group_request(&test_peer_id(), &GroupRequestPayload { group_id: group.id })
+            .unwrap();
+        assert_eq!(response.group, Some(group));
+
+        // Cached copies of other owners' groups are not passed on
+        let (owner_key, owner_pubkey) = generate_identity();
+        let mut foreign = nodalync_types::Group::new(
+            peer_id_from_public_key(&owner_pubkey),
+            owner_pubkey,
+            "Reviewers",
+            vec![],
+            1,
+            current_timestamp(),
+        );
+        nodalync_valid::sign_group(&owner_key, &mut foreign);
+        ops.state.groups.store(&foreign, 0).unwrap();
+        let response = ops
+            .handle_group_request(
+                &test_peer_id(),
+                &GroupRequestPayload {
+                    group_id: foreign.id,
+                },
+            )
+            .unwrap();
+        assert!(response.group.is_none());
     }
 
     #[tokio::test]
     async fn test_handle_channel_accept_success() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let peer = test_peer_id();
 
         // Simulate initiator opening a channel (creates in Opening state)
@@ -1497,7 +3041,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_handle_channel_accept_wrong_channel_id() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let peer = test_peer_id();
 
         // Open a channel
@@ -1519,7 +3063,7 @@ mod tests {
 
     #[test]
     fn test_handle_channel_accept_wrong_state() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let peer = test_peer_id();
         let channel_id = content_hash(b"test channel");
 
@@ -1541,7 +3085,7 @@ mod tests {
 
     #[test]
     fn test_handle_channel_accept_no_channel() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let peer = test_peer_id();
 
         // Try to accept without having opened a channel
@@ -1561,7 +3105,7 @@ mod tests {
         // This test verifies that paid content queries are rejected without
         // on-chain settlement configured. This is a CRITICAL security property:
         // content providers must not deliver content without payment confirmation.
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create and publish content
         let content = b"Content for distribution test";
@@ -1591,9 +3135,17 @@ mod tests {
         let request = QueryRequestPayload {
             hash,
             query: None,
-            payment,
+            payment: Some(payment),
             version_spec: None,
             payment_nonce: 1,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
         };
 
         // Without settlement configured, paid queries MUST be rejected
@@ -1605,6 +3157,182 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_paid_query_with_app_fee() {
+        use crate::config::OpsConfig;
+        use nodalync_econ::AppFee;
+        use nodalync_test_utils::MockSettlement;
+
+        let temp_dir = TempDir::new().unwrap();
+        let state = nodalync_store::NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let app = test_peer_id();
+        let ops_config = OpsConfig::default().with_app_fee(AppFee::new(app, 200).unwrap());
+        let mock_settle = Arc::new(MockSettlement::new());
+        let ops = DefaultNodeOperations::with_config_and_settlement(
+            state,
+            test_peer_id(),
+            ops_config,
+            mock_settle.clone(),
+        );
+
+        let content = b"App fee content";
+        let hash = ops
+            .create_content(content, Metadata::new("App Fee", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 1_000)
+            .await
+            .unwrap();
+
+        let requester = test_peer_id();
+        let channel_id = content_hash(b"app-fee-channel");
+        ops.accept_payment_channel(&channel_id, &requester, 5_000, 1_000)
+            .unwrap();
+
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        let request = QueryRequestPayload {
+            hash,
+            query: None,
+            payment: Some(create_test_payment_with_provenance(
+                1_000,
+                manifest.owner,
+                hash,
+                channel_id,
+                manifest.provenance.root_l0l1.clone(),
+            )),
+            version_spec: None,
+            payment_nonce: 1,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        };
+        let response = ops
+            .handle_query_request(&requester, &request)
+            .await
+            .unwrap();
+
+        // 2% to the app, reported in the receipt
+        assert_eq!(response.payment_receipt.amount, 1_000);
+        assert_eq!(response.payment_receipt.app_fee, 20);
+        assert_eq!(response.payment_receipt.app_fee_recipient, Some(app));
+
+        // and settled alongside the owner's share
+        let batches = mock_settle.settled_batches();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].total_amount(), 1_000);
+        let app_entry = batches[0]
+            .entries
+            .iter()
+            .find(|e| e.recipient == app)
+            .unwrap();
+        assert_eq!(app_entry.amount, 20);
+
+        // The app's share is payable, not our revenue
+        let balances = ops.ledger_balances().unwrap();
+        assert!(balances.contains(&(nodalync_store::LedgerAccount::Revenue, 980 - 49)));
+        assert!(balances.contains(&(nodalync_store::LedgerAccount::Fees, 49)));
+    }
+
+    #[tokio::test]
+    async fn test_referred_paid_query() {
+        use crate::config::OpsConfig;
+        use nodalync_test_utils::MockSettlement;
+
+        let temp_dir = TempDir::new().unwrap();
+        let state = nodalync_store::NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let mock_settle = Arc::new(MockSettlement::new());
+        let ops = DefaultNodeOperations::with_config_and_settlement(
+            state,
+            test_peer_id(),
+            OpsConfig::default(),
+            mock_settle.clone(),
+        );
+
+        let content = b"Referred content";
+        let hash = ops
+            .create_content(content, Metadata::new("Referred", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 1_000)
+            .await
+            .unwrap();
+
+        let requester = test_peer_id();
+        let referrer = test_peer_id();
+        let channel_id = content_hash(b"referral-channel");
+        ops.accept_payment_channel(&channel_id, &requester, 5_000, 1_000)
+            .unwrap();
+
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        let mut request = QueryRequestPayload {
+            hash,
+            query: None,
+            payment: Some(create_test_payment_with_provenance(
+                1_000,
+                manifest.owner,
+                hash,
+                channel_id,
+                manifest.provenance.root_l0l1.clone(),
+            )),
+            version_spec: None,
+            payment_nonce: 1,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: Some(referrer),
+            entitlement: None,
+        };
+
+        // Without a referral policy the referrer can't be paid
+        let result = ops.handle_query_request(&requester, &request).await;
+        assert!(matches!(
+            result,
+            Err(OpsError::Validation(
+                nodalync_valid::ValidationError::InvalidReferral { .. }
+            ))
+        ));
+
+        // Nor can the requester refer itself
+        ops.set_content_referral(&hash, Some(1_000)).unwrap();
+        request.referrer = Some(requester);
+        assert!(ops
+            .handle_query_request(&requester, &request)
+            .await
+            .is_err());
+
+        request.referrer = Some(referrer);
+        let response = ops
+            .handle_query_request(&requester, &request)
+            .await
+            .unwrap();
+
+        // 10% to the referrer, reported in the receipt
+        assert_eq!(response.payment_receipt.referral_fee, 100);
+        assert_eq!(response.payment_receipt.referrer, Some(referrer));
+
+        // and settled alongside the owner's share
+        let batches = mock_settle.settled_batches();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].total_amount(), 1_000);
+        let referrer_entry = batches[0]
+            .entries
+            .iter()
+            .find(|e| e.recipient == referrer)
+            .unwrap();
+        assert_eq!(referrer_entry.amount, 100);
+
+        // The referrer's cut is payable, not our revenue
+        let balances = ops.ledger_balances().unwrap();
+        assert!(balances.contains(&(nodalync_store::LedgerAccount::Revenue, 900 - 45)));
+        assert!(balances.contains(&(nodalync_store::LedgerAccount::Fees, 45)));
+    }
+
     #[tokio::test]
     async fn test_nonce_updated_before_settlement_for_replay_protection() {
         // This test validates that channel nonces are updated BEFORE settlement
@@ -1617,7 +3345,7 @@ mod tests {
         // By updating the nonce early, we ensure that even if settlement fails,
         // the same nonce cannot be reused. The client must increment the nonce
         // for any retry. This is more conservative and prevents double-spend.
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let content = b"Premium knowledge content";
         let requester = test_peer_id();
 
@@ -1656,9 +3384,17 @@ mod tests {
         let request = QueryRequestPayload {
             hash,
             query: None,
-            payment: payment.clone(),
+            payment: Some(payment.clone()),
             version_spec: None,
             payment_nonce: 1,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
         };
         let result = ops.handle_query_request(&requester, &request).await;
         assert!(
@@ -1679,9 +3415,17 @@ mod tests {
         let request2 = QueryRequestPayload {
             hash,
             query: None,
-            payment: payment.clone(),
+            payment: Some(payment.clone()),
             version_spec: None,
-            payment_nonce: 1, // Same nonce - should fail
+            payment_nonce: 1, // Same nonce - should fail,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
         };
         let result2 = ops.handle_query_request(&requester, &request2).await;
         assert!(
@@ -1695,7 +3439,7 @@ mod tests {
     async fn test_replay_attack_rejected_before_settlement() {
         // This test verifies that replay attacks (same nonce) are rejected
         // even without settlement configured. This is a security validation.
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let content = b"Premium knowledge content";
         let requester = test_peer_id();
 
@@ -1735,9 +3479,17 @@ mod tests {
         let request = QueryRequestPayload {
             hash,
             query: None,
-            payment,
+            payment: Some(payment),
             version_spec: None,
-            payment_nonce: 3, // Old nonce (current is 5)
+            payment_nonce: 3, // Old nonce (current is 5),
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
         };
 
         let result = ops.handle_query_request(&requester, &request).await;
@@ -1748,9 +3500,105 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_replay_rejected_by_tracked_nonce_after_crash() {
+        let (ops, _temp) = create_test_ops();
+        let content = b"Premium knowledge content";
+        let requester = test_peer_id();
+
+        let meta = Metadata::new("Premium Knowledge", content.len() as u64);
+        let hash = ops.create_content(content, meta).unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 100)
+            .await
+            .unwrap();
+
+        let channel_id = content_hash(b"test-tracked-nonce-channel");
+        ops.accept_payment_channel(&channel_id, &requester, 500, 1000)
+            .unwrap();
+
+        // Nonce 3 was accepted but we crashed before the channel row was updated
+        ops.state
+            .channels
+            .record_payment_nonce(
+                &channel_id,
+                &requester,
+                PaymentDirection::Received,
+                3,
+                current_timestamp(),
+            )
+            .unwrap();
+        assert_eq!(
+            ops.state.channels.get(&requester).unwrap().unwrap().nonce,
+            0
+        );
+
+        let manifest = ops.state.manifests.load(&hash).unwrap().unwrap();
+        let payment = Payment::new(
+            content_hash(b"payment-tracked"),
+            channel_id,
+            100,
+            ops.peer_id(),
+            hash,
+            manifest.provenance.root_l0l1.clone(),
+            current_timestamp(),
+            Signature::from_bytes([0u8; 64]),
+        );
+
+        for payment_nonce in [2, 3] {
+            let request = QueryRequestPayload {
+                hash,
+                query: None,
+                payment: Some(payment.clone()),
+                version_spec: None,
+                payment_nonce,
+                capability: None,
+                recipient_key: None,
+                challenge_response: None,
+                request_id: None,
+                section: None,
+                unit: None,
+                referrer: None,
+                entitlement: None,
+            };
+            let result = ops.handle_query_request(&requester, &request).await;
+            assert!(
+                matches!(result, Err(OpsError::PaymentValidationFailed(_))),
+                "Nonce {} is at or below the tracked nonce: {:?}",
+                payment_nonce,
+                result
+            );
+        }
+
+        // A fresh nonce passes validation (and fails later, at settlement)
+        let request = QueryRequestPayload {
+            hash,
+            query: None,
+            payment: Some(payment),
+            version_spec: None,
+            payment_nonce: 4,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        };
+        let result = ops.handle_query_request(&requester, &request).await;
+        assert!(matches!(result, Err(OpsError::SettlementRequired)));
+        let nonces = ops
+            .state
+            .channels
+            .payment_nonces(&channel_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(nonces.last_received, 4);
+    }
+
     #[tokio::test]
     async fn test_free_content_no_channel_needed() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let content = b"Free content for everyone";
         let requester = test_peer_id();
 
@@ -1766,9 +3614,17 @@ mod tests {
         let request = QueryRequestPayload {
             hash,
             query: None,
-            payment,
+            payment: Some(payment),
             version_spec: None,
             payment_nonce: 0,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
         };
 
         let result = ops.handle_query_request(&requester, &request).await;
@@ -1776,9 +3632,82 @@ mod tests {
         assert_eq!(result.unwrap().content, content.to_vec());
     }
 
+    #[tokio::test]
+    async fn test_free_query_without_payment() {
+        use crate::config::OpsConfig;
+        use nodalync_test_utils::MockSettlement;
+
+        let temp_dir = TempDir::new().unwrap();
+        let state = nodalync_store::NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let mock_settle = Arc::new(MockSettlement::new());
+        let ops = DefaultNodeOperations::with_config_and_settlement(
+            state,
+            test_peer_id(),
+            OpsConfig::default(),
+            mock_settle.clone(),
+        );
+
+        let content = b"Free content, no payment";
+        let free = ops
+            .create_content(content, Metadata::new("Free", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&free, Visibility::Shared, 0)
+            .await
+            .unwrap();
+        let paid_content = b"Paid content";
+        let paid = ops
+            .create_content(
+                paid_content,
+                Metadata::new("Paid", paid_content.len() as u64),
+            )
+            .unwrap();
+        ops.publish_content(&paid, Visibility::Shared, 100)
+            .await
+            .unwrap();
+
+        let request = |hash| QueryRequestPayload {
+            hash,
+            query: None,
+            payment: None,
+            version_spec: None,
+            payment_nonce: 0,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        };
+        let requester = test_peer_id();
+
+        let response = ops
+            .handle_query_request(&requester, &request(free))
+            .await
+            .unwrap();
+        assert_eq!(response.content, content.to_vec());
+        assert_eq!(response.payment_receipt.amount, 0);
+        assert_eq!(response.manifest.economics.total_queries, 1);
+
+        // Paid content still needs a payment
+        assert!(matches!(
+            ops.handle_query_request(&requester, &request(paid)).await,
+            Err(OpsError::PaymentInsufficient)
+        ));
+
+        // No channel, payment or settlement, but the query is counted
+        assert!(ops.state.channels.get(&requester).unwrap().is_none());
+        assert!(mock_settle.settled_batches().is_empty());
+        let stats = ops.content_stats(&free).unwrap();
+        assert_eq!(stats.queries, 1);
+        assert_eq!(stats.free_queries, 1);
+        assert_eq!(stats.revenue, 0);
+    }
+
     #[tokio::test]
     async fn test_paid_content_requires_channel() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
         let content = b"Premium paid content";
         let requester = test_peer_id();
 
@@ -1795,9 +3724,17 @@ mod tests {
         let request = QueryRequestPayload {
             hash,
             query: None,
-            payment,
+            payment: Some(payment),
             version_spec: None,
             payment_nonce: 1,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
         };
 
         let result = ops.handle_query_request(&requester, &request).await;
@@ -1810,7 +3747,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_handle_search_request_matches_content() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create and publish content
         let content = b"Blockchain and distributed ledger technology overview";
@@ -1837,9 +3774,137 @@ mod tests {
         assert!(response.results.iter().any(|r| r.hash == hash));
     }
 
+    #[tokio::test]
+    async fn test_broadcast_announcement_signature_checked() {
+        use nodalync_types::{ContentType, L1Summary};
+        use nodalync_valid::sign_announcement;
+        use nodalync_wire::{create_message, encode_message, encode_payload};
+
+        let (ops, _temp) = create_test_ops();
+        let (private_key, public_key) = generate_identity();
+        let publisher = peer_id_from_public_key(&public_key);
+
+        let announcement = |content: &[u8], title: &str| {
+            let hash = content_hash(content);
+            AnnouncePayload {
+                hash,
+                content_type: ContentType::L0,
+                title: title.to_string(),
+                l1_summary: L1Summary::empty(hash),
+                price: 100,
+                addresses: vec!["/ip4/127.0.0.1/tcp/9000".to_string()],
+                publisher_peer_id: Some("12D3KooWPublisher".to_string()),
+                publisher_key: None,
+                signature: None,
+                delegation: None,
+            }
+        };
+        let broadcast = |payload: &AnnouncePayload| {
+            let bytes = encode_payload(payload).unwrap();
+            let message = create_message(MessageType::Announce, bytes, publisher, 0, &private_key);
+            encode_message(&message).unwrap()
+        };
+        let topic = "/nodalync/announce/1.0.0";
+
+        let mut signed = announcement(b"signed", "Signed Notes");
+        sign_announcement(&private_key, &mut signed).unwrap();
+        let mut forged = announcement(b"forged", "Forged Notes");
+        sign_announcement(&private_key, &mut forged).unwrap();
+        forged.publisher_peer_id = Some("12D3KooWAttacker".to_string());
+        let unsigned = announcement(b"unsigned", "Unsigned Notes");
+
+        for payload in [&signed, &forged, &unsigned] {
+            ops.handle_broadcast_announcement(topic, &broadcast(payload))
+                .await
+                .unwrap();
+        }
+
+        // The forged announcement is dropped
+        assert!(ops.state.get_announcement(&forged.hash).is_none());
+        assert_eq!(
+            ops.state.get_announcement(&signed.hash),
+            Some(signed.clone())
+        );
+
+        let results = ops.search_network("Notes", None, 10).await.unwrap();
+        assert_eq!(results.len(), 2);
+        let signed_result = results.iter().find(|r| r.hash == signed.hash).unwrap();
+        assert!(signed_result.verified_publisher);
+        assert_eq!(signed_result.owner, publisher);
+        let unsigned_result = results.iter().find(|r| r.hash == unsigned.hash).unwrap();
+        assert!(!unsigned_result.verified_publisher);
+        assert_eq!(unsigned_result.owner, nodalync_crypto::UNKNOWN_PEER_ID);
+    }
+
+    #[tokio::test]
+    async fn test_handle_search_request_applies_filters() {
+        let (ops, _temp) = create_test_ops();
+
+        let mut hashes = Vec::new();
+        for (content, title, price, tags) in [
+            (
+                &b"Cheap guide to gardening"[..],
+                "Gardening Basics",
+                10,
+                vec!["garden"],
+            ),
+            (
+                b"Expensive guide to gardening",
+                "Gardening Deep Dive",
+                500,
+                vec!["garden", "soil"],
+            ),
+        ] {
+            let meta = Metadata::new(title, content.len() as u64);
+            let hash = ops.create_content(content, meta).unwrap();
+            ops.publish_content(&hash, Visibility::Shared, price)
+                .await
+                .unwrap();
+            // Publishing derives tags from the L1 summary, so set them after
+            let mut manifest = ops.state.manifests.load(&hash).unwrap().unwrap();
+            manifest.metadata.tags = tags.iter().map(|t| t.to_string()).collect();
+            ops.state.manifests.update(&manifest).unwrap();
+            hashes.push(hash);
+        }
+        let (cheap, pricey) = (hashes[0], hashes[1]);
+
+        let requester = test_peer_id();
+        let search = |filters: SearchFilters| SearchPayload {
+            query: "gardening".to_string(),
+            filters: Some(filters),
+            limit: 10,
+            offset: 0,
+        };
+        let found = |response: SearchResponsePayload| -> Vec<Hash> {
+            response.results.iter().map(|r| r.hash).collect()
+        };
+
+        let request = search(SearchFilters {
+            min_price: Some(100),
+            ..Default::default()
+        });
+        let response = ops.handle_search_request(&requester, &request).unwrap();
+        assert_eq!(found(response), vec![pricey]);
+
+        let request = search(SearchFilters {
+            tags: Some(vec!["garden".to_string()]),
+            max_price: Some(100),
+            ..Default::default()
+        });
+        let response = ops.handle_search_request(&requester, &request).unwrap();
+        assert_eq!(found(response), vec![cheap]);
+
+        let request = search(SearchFilters {
+            owner: Some(test_peer_id()),
+            ..Default::default()
+        });
+        let response = ops.handle_search_request(&requester, &request).unwrap();
+        assert!(response.results.is_empty());
+    }
+
     #[test]
     fn test_handle_search_request_empty_results() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Search for content that does not exist
         let requester = test_peer_id();
@@ -1857,7 +3922,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_handle_network_event_unknown_type() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create a network event with a PeerConnected type (not a request)
         let peer = nodalync_net::PeerId::random();
@@ -1867,6 +3932,62 @@ mod tests {
         assert!(result.is_none(), "PeerConnected should return None");
     }
 
+    #[tokio::test]
+    async fn test_handle_peer_info_request() {
+        use nodalync_wire::{create_message, encode_message, encode_payload, Capability};
+
+        let (ops, _temp) = create_keyed_test_ops();
+        let (private_key, public_key) = generate_identity();
+        let sender = peer_id_from_public_key(&public_key);
+        let libp2p_peer = nodalync_net::PeerId::random();
+        let info = PeerInfoPayload {
+            peer_id: sender,
+            public_key,
+            addresses: vec!["/ip4/10.0.0.2/tcp/9000".to_string()],
+            capabilities: vec![Capability::Query, Capability::Relay],
+            content_count: 0,
+            uptime: 5,
+            did: None,
+        };
+        let request = |info: &PeerInfoPayload, key: &nodalync_crypto::PrivateKey| {
+            let payload = encode_payload(info).unwrap();
+            encode_message(&create_message(
+                MessageType::PeerInfo,
+                payload,
+                sender,
+                current_timestamp(),
+                key,
+            ))
+            .unwrap()
+        };
+
+        // Signed by a key other than the advertised one
+        let (other_key, _) = generate_identity();
+        let response = ops
+            .handle_inbound_request(&libp2p_peer, &request(&info, &other_key))
+            .await
+            .unwrap();
+        assert!(response.is_none());
+        assert!(ops.state.peers.get(&sender).unwrap().is_none());
+
+        let (message_type, bytes) = ops
+            .handle_inbound_request(&libp2p_peer, &request(&info, &private_key))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(message_type, MessageType::PeerInfo);
+        let ours: PeerInfoPayload = decode_payload(&bytes).unwrap();
+        assert_eq!(ours.peer_id, ops.peer_id());
+        assert_eq!(ours.capabilities, ops.local_capabilities());
+
+        let stored = ops.state.peers.get(&sender).unwrap().unwrap();
+        assert_eq!(
+            stored.capabilities,
+            Some(vec![Capability::Query, Capability::Relay])
+        );
+        assert!(!ops.peer_supports(&sender, Capability::ChunkedTransfer));
+    }
+
     // =========================================================================
     // Channel Open Security Tests
     // =========================================================================
@@ -1886,7 +4007,7 @@ mod tests {
         let ops_config = OpsConfig::default()
             .with_channel(ChannelConfig::default().with_max_accept_deposit(500_0000_0000));
 
-        let mut ops = DefaultNodeOperations::with_config(state, peer_id, ops_config);
+        let ops = DefaultNodeOperations::with_config(state, peer_id, ops_config);
 
         let requester = test_peer_id();
         let channel_id = content_hash(b"cap test channel");
@@ -1934,7 +4055,7 @@ mod tests {
         let ops_config =
             OpsConfig::default().with_channel(ChannelConfig::new(100_0000_0000, 1000_0000_0000));
 
-        let mut ops = DefaultNodeOperations::with_config(state, peer_id, ops_config);
+        let ops = DefaultNodeOperations::with_config(state, peer_id, ops_config);
 
         let requester = test_peer_id();
         let channel_id = content_hash(b"min test channel");
@@ -1980,7 +4101,7 @@ mod tests {
         // Create mock settlement with low balance to trigger deposit check
         let mock_settle = Arc::new(MockSettlement::new().with_balance(0));
 
-        let mut ops = DefaultNodeOperations::with_config_and_settlement(
+        let ops = DefaultNodeOperations::with_config_and_settlement(
             state,
             peer_id,
             ops_config,
@@ -2030,7 +4151,7 @@ mod tests {
         // Create mock settlement with low balance to trigger auto-deposit
         let mock_settle = Arc::new(MockSettlement::new().with_balance(0));
 
-        let mut ops = DefaultNodeOperations::with_config_and_settlement(
+        let ops = DefaultNodeOperations::with_config_and_settlement(
             state,
             peer_id,
             ops_config,
@@ -2081,7 +4202,7 @@ mod tests {
         // Create mock settlement with HIGH balance (above threshold)
         let mock_settle = Arc::new(MockSettlement::new().with_balance(500_0000_0000));
 
-        let mut ops = DefaultNodeOperations::with_config_and_settlement(
+        let ops = DefaultNodeOperations::with_config_and_settlement(
             state,
             peer_id,
             ops_config,
@@ -2130,7 +4251,7 @@ mod tests {
         // Create mock settlement with low balance
         let mock_settle = Arc::new(MockSettlement::new().with_balance(0));
 
-        let mut ops = DefaultNodeOperations::with_config_and_settlement(
+        let ops = DefaultNodeOperations::with_config_and_settlement(
             state,
             peer_id,
             ops_config,
@@ -2175,7 +4296,7 @@ mod tests {
     #[tokio::test]
     async fn test_channel_open_no_overflow_on_max_initial_balance() {
         // This test verifies no panic or overflow when processing u64::MAX
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         let requester = test_peer_id();
         let channel_id = content_hash(b"overflow test channel");
@@ -0,0 +1,375 @@
+//! Batched ingestion of gossiped announcements.
+//!
+//! A burst of GossipSub announcements would otherwise be written one row,
+//! and one transaction, at a time. Once
+//! [`start_announcement_ingest`](NodeOperations::start_announcement_ingest)
+//! is called, announcements that pass the filters (see
+//! [`announce_filter`](crate::announce_filter)) are queued to a background
+//! task instead. The task collects up to `batch_size` announcements, or
+//! whatever arrives within `flush_interval_ms` of the first, keeps the
+//! latest announcement per hash and stores the batch in one transaction.
+//!
+//! The queue holds `max_pending` announcements. While it is full, further
+//! announcements are dropped and the network is asked to shed incoming
+//! announcements (`Network::set_broadcast_backpressure`) until the queue
+//! has drained to half.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use nodalync_crypto::Hash;
+use nodalync_net::Network;
+use nodalync_store::AnnouncementWriter;
+use nodalync_valid::Validator;
+use nodalync_wire::AnnouncePayload;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, info, warn};
+
+use crate::config::AnnouncementIngestConfig;
+use crate::extraction::L1Extractor;
+use crate::node_ops::NodeOperations;
+
+/// Counters for announcement ingestion since it was started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnnouncementIngestStats {
+    /// Announcements queued for storage.
+    pub queued: u64,
+    /// Announcements stored.
+    pub stored: u64,
+    /// Batches written.
+    pub batches: u64,
+    /// Queued announcements superseded by a later one for the same hash
+    /// in the same batch.
+    pub deduplicated: u64,
+    /// Announcements dropped because the queue was full.
+    pub dropped_backpressure: u64,
+    /// Announcements queued and not yet stored.
+    pub pending: u64,
+    /// Whether the network is currently asked to shed announcements.
+    pub backpressure: bool,
+}
+
+/// Counters shared between the operations and the ingestion task.
+#[derive(Debug, Default)]
+struct IngestCounters {
+    queued: AtomicU64,
+    stored: AtomicU64,
+    batches: AtomicU64,
+    deduplicated: AtomicU64,
+    dropped_backpressure: AtomicU64,
+    pending: AtomicU64,
+    backpressure: AtomicBool,
+}
+
+enum IngestCommand {
+    Announce(Box<AnnouncePayload>),
+    /// Store everything queued so far, then reply.
+    Flush(oneshot::Sender<()>),
+}
+
+/// Handle to a running ingestion task.
+pub(crate) struct AnnouncementIngest {
+    tx: mpsc::Sender<IngestCommand>,
+    counters: Arc<IngestCounters>,
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Start storing accepted announcements in batches from a background
+    /// task, configured by `OpsConfig::announcement_ingest`.
+    ///
+    /// Must be called from within a Tokio runtime. Until it is called,
+    /// announcements are stored as they arrive. Calling it again has no
+    /// effect.
+    pub fn start_announcement_ingest(&mut self) {
+        if self.announcement_ingest.is_some() {
+            return;
+        }
+        let config = self.config.announcement_ingest.clone();
+        let (tx, rx) = mpsc::channel(config.max_pending);
+        let counters = Arc::new(IngestCounters::default());
+        tokio::spawn(run_ingest(
+            rx,
+            self.state.announcement_writer(),
+            self.network().cloned(),
+            Arc::clone(&counters),
+            config,
+        ));
+        self.announcement_ingest = Some(AnnouncementIngest { tx, counters });
+    }
+
+    /// Store everything queued for ingestion and wait until it is written.
+    ///
+    /// Returns immediately if ingestion isn't running.
+    pub async fn flush_announcements(&self) {
+        let Some(ingest) = &self.announcement_ingest else {
+            return;
+        };
+        let (done_tx, done_rx) = oneshot::channel();
+        if ingest.tx.send(IngestCommand::Flush(done_tx)).await.is_ok() {
+            let _ = done_rx.await;
+        }
+    }
+
+    /// Get the announcement ingestion counters.
+    pub fn announcement_ingest_stats(&self) -> AnnouncementIngestStats {
+        self.announcement_ingest
+            .as_ref()
+            .map(|ingest| ingest.counters.snapshot())
+            .unwrap_or_default()
+    }
+
+    /// Store an accepted announcement, through the ingestion queue if it is
+    /// running.
+    pub(crate) fn ingest_announcement(&self, payload: AnnouncePayload) {
+        let Some(ingest) = &self.announcement_ingest else {
+            self.state.store_announcement(payload);
+            return;
+        };
+        let counters = &ingest.counters;
+        match ingest
+            .tx
+            .try_send(IngestCommand::Announce(Box::new(payload)))
+        {
+            Ok(()) => {
+                counters.queued.fetch_add(1, Ordering::Relaxed);
+                counters.pending.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(TrySendError::Full(IngestCommand::Announce(payload))) => {
+                debug!(hash = %payload.hash, "Dropping announcement, ingestion queue full");
+                counters
+                    .dropped_backpressure
+                    .fetch_add(1, Ordering::Relaxed);
+                if !counters.backpressure.swap(true, Ordering::Relaxed) {
+                    warn!("Announcement ingestion queue full, shedding announcements");
+                    if let Some(network) = self.network() {
+                        network.set_broadcast_backpressure(true);
+                    }
+                }
+            }
+            Err(TrySendError::Full(_)) => {}
+            Err(TrySendError::Closed(IngestCommand::Announce(payload))) => {
+                self.state.store_announcement(*payload);
+            }
+            Err(TrySendError::Closed(_)) => {}
+        }
+    }
+}
+
+impl IngestCounters {
+    fn snapshot(&self) -> AnnouncementIngestStats {
+        AnnouncementIngestStats {
+            queued: self.queued.load(Ordering::Relaxed),
+            stored: self.stored.load(Ordering::Relaxed),
+            batches: self.batches.load(Ordering::Relaxed),
+            deduplicated: self.deduplicated.load(Ordering::Relaxed),
+            dropped_backpressure: self.dropped_backpressure.load(Ordering::Relaxed),
+            pending: self.pending.load(Ordering::Relaxed),
+            backpressure: self.backpressure.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Collect queued announcements into batches and store them until the
+/// operations are dropped.
+async fn run_ingest(
+    mut rx: mpsc::Receiver<IngestCommand>,
+    writer: AnnouncementWriter,
+    network: Option<Arc<dyn Network>>,
+    counters: Arc<IngestCounters>,
+    config: AnnouncementIngestConfig,
+) {
+    let flush_interval = Duration::from_millis(config.flush_interval_ms);
+    let mut batch = Vec::with_capacity(config.batch_size);
+    let mut waiters = Vec::new();
+
+    while let Some(command) = rx.recv().await {
+        let deadline = tokio::time::Instant::now() + flush_interval;
+        let mut next = Some(command);
+        while let Some(command) = next.take() {
+            match command {
+                IngestCommand::Announce(payload) => batch.push(*payload),
+                IngestCommand::Flush(done) => waiters.push(done),
+            }
+            if batch.len() >= config.batch_size || !waiters.is_empty() {
+                break;
+            }
+            next = tokio::time::timeout_at(deadline, rx.recv())
+                .await
+                .ok()
+                .flatten();
+        }
+
+        if !batch.is_empty() {
+            store_batch(std::mem::take(&mut batch), &writer, &counters).await;
+        }
+        if counters.pending.load(Ordering::Relaxed) <= config.max_pending as u64 / 2
+            && counters.backpressure.swap(false, Ordering::Relaxed)
+        {
+            info!("Announcement ingestion caught up, accepting announcements again");
+            if let Some(network) = &network {
+                network.set_broadcast_backpressure(false);
+            }
+        }
+        for done in waiters.drain(..) {
+            let _ = done.send(());
+        }
+    }
+}
+
+/// Store a batch, keeping the latest announcement per hash.
+async fn store_batch(
+    batch: Vec<AnnouncePayload>,
+    writer: &AnnouncementWriter,
+    counters: &IngestCounters,
+) {
+    let received = batch.len() as u64;
+    let (batch, duplicates) = dedupe(batch);
+    let writer = writer.clone();
+    let stored = tokio::task::spawn_blocking(move || writer.store(&batch))
+        .await
+        .unwrap_or(0);
+
+    counters
+        .deduplicated
+        .fetch_add(duplicates as u64, Ordering::Relaxed);
+    counters.stored.fetch_add(stored as u64, Ordering::Relaxed);
+    counters.batches.fetch_add(1, Ordering::Relaxed);
+    let _ = counters
+        .pending
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |pending| {
+            Some(pending.saturating_sub(received))
+        });
+    debug!(received, stored, duplicates, "Stored announcement batch");
+}
+
+/// Keep the latest announcement per hash, in first-seen order. Returns the
+/// kept announcements and how many were dropped.
+fn dedupe(batch: Vec<AnnouncePayload>) -> (Vec<AnnouncePayload>, usize) {
+    let mut positions: HashMap<Hash, usize> = HashMap::with_capacity(batch.len());
+    let mut kept: Vec<AnnouncePayload> = Vec::with_capacity(batch.len());
+    let mut duplicates = 0;
+    for payload in batch {
+        match positions.get(&payload.hash) {
+            Some(&position) => {
+                kept[position] = payload;
+                duplicates += 1;
+            }
+            None => {
+                positions.insert(payload.hash, kept.len());
+                kept.push(payload);
+            }
+        }
+    }
+    (kept, duplicates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OpsConfig;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_test_utils::MockNetwork;
+    use nodalync_types::{ContentType, L1Summary};
+    use tempfile::TempDir;
+
+    fn create_test_ops(
+        ingest: AnnouncementIngestConfig,
+    ) -> (DefaultNodeOperations, Arc<MockNetwork>, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (_, public_key) = generate_identity();
+        let network = Arc::new(MockNetwork::new());
+        let ops = DefaultNodeOperations::with_config_and_network(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default().with_announcement_ingest(ingest),
+            network.clone(),
+        );
+        (ops, network, temp_dir)
+    }
+
+    fn announcement(content: &[u8], title: &str) -> AnnouncePayload {
+        let hash = content_hash(content);
+        AnnouncePayload {
+            hash,
+            content_type: ContentType::L0,
+            title: title.to_string(),
+            l1_summary: L1Summary::empty(hash),
+            price: 0,
+            addresses: vec![],
+            publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
+        }
+    }
+
+    #[test]
+    fn test_stores_directly_until_started() {
+        let (ops, _network, _temp) = create_test_ops(AnnouncementIngestConfig::default());
+        let payload = announcement(b"direct", "Direct");
+
+        ops.ingest_announcement(payload.clone());
+
+        assert!(ops.state.get_announcement(&payload.hash).is_some());
+        assert_eq!(ops.announcement_ingest_stats(), Default::default());
+    }
+
+    #[tokio::test]
+    async fn test_batches_and_dedupes() {
+        let (mut ops, _network, _temp) =
+            create_test_ops(AnnouncementIngestConfig::default().with_flush_interval(60_000));
+        ops.start_announcement_ingest();
+
+        ops.ingest_announcement(announcement(b"a", "First"));
+        ops.ingest_announcement(announcement(b"b", "Other"));
+        ops.ingest_announcement(announcement(b"a", "Renamed"));
+        assert_eq!(ops.state.announcement_count(), 0);
+
+        ops.flush_announcements().await;
+
+        assert_eq!(ops.state.announcement_count(), 2);
+        let renamed = ops.state.get_announcement(&content_hash(b"a")).unwrap();
+        assert_eq!(renamed.title, "Renamed");
+        let stats = ops.announcement_ingest_stats();
+        assert_eq!(stats.queued, 3);
+        assert_eq!(stats.stored, 2);
+        assert_eq!(stats.deduplicated, 1);
+        assert_eq!(stats.batches, 1);
+        assert_eq!(stats.pending, 0);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_engages_backpressure() {
+        let (mut ops, network, _temp) = create_test_ops(
+            AnnouncementIngestConfig::default()
+                .with_max_pending(2)
+                .with_flush_interval(60_000),
+        );
+        ops.start_announcement_ingest();
+
+        // The ingestion task doesn't run until we yield, so the queue fills
+        for i in 0..3u8 {
+            ops.ingest_announcement(announcement(&[i], "Burst"));
+        }
+        assert!(network.broadcast_backpressure());
+        let stats = ops.announcement_ingest_stats();
+        assert_eq!((stats.queued, stats.dropped_backpressure), (2, 1));
+        assert!(stats.backpressure);
+
+        // Draining the queue releases it
+        ops.flush_announcements().await;
+        assert!(!network.broadcast_backpressure());
+        assert!(!ops.announcement_ingest_stats().backpressure);
+        assert_eq!(ops.state.announcement_count(), 2);
+    }
+}
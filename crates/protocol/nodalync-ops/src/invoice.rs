@@ -0,0 +1,488 @@
+//! Invoice operations.
+//!
+//! A payee creates and signs an invoice, then sends it to the payer (or the
+//! payer fetches it by hash). The payer pays it through the payment channel
+//! with the payee: the payment's query hash is the invoice hash, and the
+//! payee marks the invoice paid once it has validated and credited the
+//! payment. Both sides track their invoices in the store.
+
+use nodalync_crypto::{Hash, PeerId};
+use nodalync_store::{
+    ChannelStore, InvoiceDirection, InvoiceRecord, InvoiceStatus, InvoiceStore, LedgerAccount,
+    LedgerEvent,
+};
+use nodalync_types::{Amount, Invoice, DEFAULT_INVOICE_EXPIRY_MS};
+use nodalync_valid::{sign_invoice, validate_invoice, Validator};
+use nodalync_wire::{
+    decode_payload, InvoiceAckPayload, InvoicePayPayload, InvoicePayload, InvoiceRequestPayload,
+    MessageType,
+};
+use tracing::info;
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Create and sign an invoice for a payment to us.
+    ///
+    /// The invoice expires `expires_in` milliseconds from now (default
+    /// [`DEFAULT_INVOICE_EXPIRY_MS`]) and is tracked as issued until paid.
+    /// Requires the private key for signing.
+    pub fn create_invoice(
+        &self,
+        amount: Amount,
+        memo: impl Into<String>,
+        reference: Option<Hash>,
+        expires_in: Option<u64>,
+    ) -> OpsResult<Invoice> {
+        let private_key = self.private_key().ok_or(OpsError::PrivateKeyRequired)?;
+        let created_at = current_timestamp();
+        let expires_at = created_at.saturating_add(expires_in.unwrap_or(DEFAULT_INVOICE_EXPIRY_MS));
+
+        let mut invoice = Invoice::new(
+            self.peer_id(),
+            private_key.public_key(),
+            amount,
+            memo,
+            reference,
+            created_at,
+            expires_at,
+        );
+        sign_invoice(private_key, &mut invoice);
+        validate_invoice(&invoice)?;
+
+        self.state.invoices.insert(&InvoiceRecord::new(
+            invoice.clone(),
+            InvoiceDirection::Issued,
+        ))?;
+        info!(hash = %invoice.hash, amount, "Created invoice");
+        Ok(invoice)
+    }
+
+    /// Get a tracked invoice by hash.
+    pub fn get_invoice(&self, hash: &Hash) -> OpsResult<Option<InvoiceRecord>> {
+        Ok(self.state.invoices.get(hash)?)
+    }
+
+    /// List tracked invoices, newest first.
+    pub fn list_invoices(
+        &self,
+        direction: Option<InvoiceDirection>,
+        status: Option<InvoiceStatus>,
+    ) -> OpsResult<Vec<InvoiceRecord>> {
+        Ok(self.state.invoices.list(direction, status)?)
+    }
+
+    /// Send one of our invoices to the peer expected to pay it.
+    pub async fn send_invoice(&self, hash: &Hash, payer: &PeerId) -> OpsResult<()> {
+        let record = self.issued_invoice(hash)?;
+
+        let network = self
+            .network()
+            .cloned()
+            .ok_or_else(|| OpsError::invalid_operation("network required to send invoices"))?;
+        let libp2p_peer = network
+            .libp2p_peer_id(payer)
+            .ok_or(OpsError::PeerIdNotFound)?;
+
+        let ack = network
+            .send_invoice(
+                libp2p_peer,
+                InvoicePayload {
+                    invoice: record.invoice,
+                },
+            )
+            .await?;
+        check_ack(&ack)
+    }
+
+    /// Fetch an invoice from its payee and track it as received.
+    pub async fn fetch_invoice(&self, payee: &PeerId, hash: &Hash) -> OpsResult<Invoice> {
+        let network = self
+            .network()
+            .cloned()
+            .ok_or_else(|| OpsError::invalid_operation("network required to fetch invoices"))?;
+        let libp2p_peer = network
+            .libp2p_peer_id(payee)
+            .ok_or(OpsError::PeerIdNotFound)?;
+
+        let response = network
+            .request_invoice(
+                libp2p_peer,
+                InvoiceRequestPayload {
+                    invoice_hash: *hash,
+                },
+            )
+            .await?;
+
+        let invoice = match response.message_type {
+            MessageType::Invoice => {
+                let payload: InvoicePayload = decode_payload(&response.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                payload.invoice
+            }
+            MessageType::InvoiceAck => {
+                let ack: InvoiceAckPayload = decode_payload(&response.payload)
+                    .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+                check_ack(&ack)?;
+                return Err(OpsError::InvoiceNotFound(*hash));
+            }
+            other => {
+                return Err(OpsError::invalid_operation(format!(
+                    "unexpected invoice response: {:?}",
+                    other
+                )))
+            }
+        };
+
+        if invoice.hash != *hash || invoice.payee != *payee {
+            return Err(OpsError::invalid_operation(
+                "payee returned a different invoice",
+            ));
+        }
+        self.receive_invoice(invoice.clone())?;
+        Ok(invoice)
+    }
+
+    /// Pay a received invoice through the payment channel with its payee.
+    ///
+    /// The payment is signed with a tracked nonce and only applied to the
+    /// channel once the payee accepts it.
+    pub async fn pay_invoice(&self, hash: &Hash) -> OpsResult<InvoiceRecord> {
+        let record = self
+            .state
+            .invoices
+            .get(hash)?
+            .filter(|r| r.direction == InvoiceDirection::Received)
+            .ok_or(OpsError::InvoiceNotFound(*hash))?;
+        let invoice = &record.invoice;
+
+        if record.status == InvoiceStatus::Paid {
+            return Err(OpsError::invalid_operation("invoice already paid"));
+        }
+        if record.is_expired(current_timestamp()) {
+            return Err(OpsError::invalid_operation("invoice expired"));
+        }
+
+        let channel = self
+            .state
+            .channels
+            .get(&invoice.payee)?
+            .ok_or(OpsError::ChannelRequired)?;
+        if !channel.is_open() {
+            return Err(OpsError::ChannelNotOpen);
+        }
+        if channel.my_balance < invoice.amount {
+            return Err(OpsError::InsufficientChannelBalance);
+        }
+
+        let network = self
+            .network()
+            .cloned()
+            .ok_or_else(|| OpsError::invalid_operation("network required to pay invoices"))?;
+        let libp2p_peer = network
+            .libp2p_peer_id(&invoice.payee)
+            .ok_or(OpsError::PeerIdNotFound)?;
+
+        let (payment, payment_nonce) = self.sign_tracked_payment(
+            &invoice.payee,
+            &channel,
+            invoice.amount,
+            invoice.hash,
+            vec![],
+        )?;
+
+        let ack = network
+            .send_invoice_payment(
+                libp2p_peer,
+                InvoicePayPayload {
+                    invoice_hash: invoice.hash,
+                    payment: payment.clone(),
+                    payment_nonce,
+                },
+            )
+            .await?;
+        check_ack(&ack)?;
+
+        self.apply_channel_payment(&invoice.payee, payment.clone())?;
+        self.record_ledger_transfer(
+            LedgerEvent::InvoicePaid,
+            invoice.hash,
+            LedgerAccount::Channels,
+            LedgerAccount::QuerySpend,
+            payment.amount,
+        );
+
+        let me = self.peer_id();
+        self.state
+            .invoices
+            .mark_paid(hash, &me, &payment.id, current_timestamp())?;
+        info!(hash = %invoice.hash, amount = payment.amount, "Paid invoice");
+
+        self.state
+            .invoices
+            .get(hash)?
+            .ok_or(OpsError::InvoiceNotFound(*hash))
+    }
+
+    /// Validate an invoice sent to us and track it as received.
+    ///
+    /// Returns `false` if the invoice was already tracked.
+    pub(crate) fn receive_invoice(&self, invoice: Invoice) -> OpsResult<bool> {
+        validate_invoice(&invoice)?;
+        if invoice.is_expired(current_timestamp()) {
+            return Err(OpsError::invalid_operation("invoice expired"));
+        }
+
+        let inserted = self
+            .state
+            .invoices
+            .insert(&InvoiceRecord::new(invoice, InvoiceDirection::Received))?;
+        Ok(inserted)
+    }
+
+    /// Get one of the invoices we issued.
+    pub(crate) fn issued_invoice(&self, hash: &Hash) -> OpsResult<InvoiceRecord> {
+        self.state
+            .invoices
+            .get(hash)?
+            .filter(|r| r.direction == InvoiceDirection::Issued)
+            .ok_or(OpsError::InvoiceNotFound(*hash))
+    }
+}
+
+/// Turn a rejecting ack into an error.
+fn check_ack(ack: &InvoiceAckPayload) -> OpsResult<()> {
+    if ack.accepted {
+        Ok(())
+    } else {
+        Err(OpsError::InvoiceRejected(
+            ack.reason
+                .clone()
+                .unwrap_or_else(|| "no reason given".to_string()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_ops::DefaultNodeOperations;
+    use crate::Network;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_store::{LedgerStore, NodeState, NodeStateConfig};
+    use nodalync_test_utils::MockNetwork;
+    use nodalync_types::Channel;
+    use nodalync_wire::create_message;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let config = NodeStateConfig::new(temp_dir.path());
+        let state = NodeState::open(config).unwrap();
+
+        let (private_key, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+
+        let mut ops = DefaultNodeOperations::with_defaults(state, peer_id);
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    /// An invoice signed by a fresh payee.
+    fn foreign_invoice(amount: Amount, expires_in: u64) -> Invoice {
+        let (private_key, public_key) = generate_identity();
+        let now = current_timestamp();
+        let mut invoice = Invoice::new(
+            peer_id_from_public_key(&public_key),
+            public_key,
+            amount,
+            "Consulting",
+            None,
+            now,
+            now + expires_in,
+        );
+        sign_invoice(&private_key, &mut invoice);
+        invoice
+    }
+
+    fn open_channel(ops: &mut DefaultNodeOperations, peer: &PeerId, my_balance: Amount) {
+        let mut channel = Channel::new(content_hash(b"channel"), *peer, my_balance, 1_000);
+        channel.mark_open(my_balance, 1_000);
+        ops.state.channels.create(peer, channel).unwrap();
+    }
+
+    fn attach_network(ops: &mut DefaultNodeOperations, network: MockNetwork, peer: &PeerId) {
+        let libp2p_peer = nodalync_net::PeerId::random();
+        network.register_peer_mapping(libp2p_peer, *peer);
+        ops.set_network(Arc::new(network));
+    }
+
+    #[test]
+    fn test_create_invoice() {
+        let (ops, _temp) = create_test_ops();
+        let reference = content_hash(b"dataset");
+
+        let invoice = ops
+            .create_invoice(500, "Dataset license", Some(reference), None)
+            .unwrap();
+        assert!(validate_invoice(&invoice).is_ok());
+        assert_eq!(invoice.payee, ops.peer_id());
+        assert_eq!(
+            invoice.expires_at - invoice.created_at,
+            DEFAULT_INVOICE_EXPIRY_MS
+        );
+
+        let record = ops.get_invoice(&invoice.hash).unwrap().unwrap();
+        assert_eq!(record.direction, InvoiceDirection::Issued);
+        assert_eq!(record.status, InvoiceStatus::Unpaid);
+        assert_eq!(
+            ops.list_invoices(Some(InvoiceDirection::Issued), None)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Zero amounts are rejected
+        assert!(ops.create_invoice(0, "nothing", None, None).is_err());
+    }
+
+    #[test]
+    fn test_create_invoice_requires_key() {
+        let (mut ops, _temp) = create_test_ops();
+        ops.clear_private_key();
+        assert!(matches!(
+            ops.create_invoice(500, "memo", None, None),
+            Err(OpsError::PrivateKeyRequired)
+        ));
+    }
+
+    #[test]
+    fn test_receive_invoice_validates() {
+        let (ops, _temp) = create_test_ops();
+
+        let invoice = foreign_invoice(300, 60_000);
+        assert!(ops.receive_invoice(invoice.clone()).unwrap());
+        assert!(!ops.receive_invoice(invoice.clone()).unwrap());
+
+        let mut tampered = foreign_invoice(300, 60_000);
+        tampered.amount = 1;
+        assert!(ops.receive_invoice(tampered).is_err());
+
+        let mut expired = foreign_invoice(300, 60_000);
+        expired.created_at = 1;
+        expired.expires_at = 2;
+        assert!(ops.receive_invoice(expired).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pay_invoice() {
+        let (mut ops, _temp) = create_test_ops();
+        let invoice = foreign_invoice(300, 60_000);
+        ops.receive_invoice(invoice.clone()).unwrap();
+        open_channel(&mut ops, &invoice.payee, 1_000);
+
+        let network = MockNetwork::new().with_invoice_ack(InvoiceAckPayload {
+            invoice_hash: invoice.hash,
+            accepted: true,
+            reason: None,
+        });
+        attach_network(&mut ops, network, &invoice.payee);
+
+        let record = ops.pay_invoice(&invoice.hash).await.unwrap();
+        assert_eq!(record.status, InvoiceStatus::Paid);
+        assert_eq!(record.payer, Some(ops.peer_id()));
+        assert!(record.payment_id.is_some());
+
+        let channel = ops.state.channels.get(&invoice.payee).unwrap().unwrap();
+        assert_eq!(channel.my_balance, 700);
+        assert_eq!(
+            ops.state.ledger.balance(LedgerAccount::QuerySpend).unwrap(),
+            300
+        );
+
+        // Paying twice is refused
+        assert!(ops.pay_invoice(&invoice.hash).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pay_invoice_rejected_leaves_channel() {
+        let (mut ops, _temp) = create_test_ops();
+        let invoice = foreign_invoice(300, 60_000);
+        ops.receive_invoice(invoice.clone()).unwrap();
+        open_channel(&mut ops, &invoice.payee, 1_000);
+
+        let network = MockNetwork::new().with_invoice_ack(InvoiceAckPayload {
+            invoice_hash: invoice.hash,
+            accepted: false,
+            reason: Some("invoice expired".to_string()),
+        });
+        attach_network(&mut ops, network, &invoice.payee);
+
+        assert!(matches!(
+            ops.pay_invoice(&invoice.hash).await,
+            Err(OpsError::InvoiceRejected(_))
+        ));
+        let channel = ops.state.channels.get(&invoice.payee).unwrap().unwrap();
+        assert_eq!(channel.my_balance, 1_000);
+        assert_eq!(
+            ops.get_invoice(&invoice.hash).unwrap().unwrap().status,
+            InvoiceStatus::Unpaid
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pay_invoice_checks_channel_balance() {
+        let (mut ops, _temp) = create_test_ops();
+        let invoice = foreign_invoice(300, 60_000);
+        ops.receive_invoice(invoice.clone()).unwrap();
+
+        assert!(matches!(
+            ops.pay_invoice(&invoice.hash).await,
+            Err(OpsError::ChannelRequired)
+        ));
+
+        open_channel(&mut ops, &invoice.payee, 100);
+        assert!(matches!(
+            ops.pay_invoice(&invoice.hash).await,
+            Err(OpsError::InsufficientChannelBalance)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_invoice() {
+        let (mut ops, _temp) = create_test_ops();
+        let invoice = foreign_invoice(300, 60_000);
+        let payload = nodalync_wire::encode_payload(&InvoicePayload {
+            invoice: invoice.clone(),
+        })
+        .unwrap();
+        let (payee_key, _) = generate_identity();
+        let response = create_message(
+            MessageType::Invoice,
+            payload,
+            invoice.payee,
+            current_timestamp(),
+            &payee_key,
+        );
+
+        let network = MockNetwork::new().with_invoice_response(invoice.hash, response);
+        attach_network(&mut ops, network, &invoice.payee);
+
+        let fetched = ops
+            .fetch_invoice(&invoice.payee, &invoice.hash)
+            .await
+            .unwrap();
+        assert_eq!(fetched, invoice);
+        assert_eq!(
+            ops.get_invoice(&invoice.hash).unwrap().unwrap().direction,
+            InvoiceDirection::Received
+        );
+    }
+}
@@ -50,7 +50,7 @@ where
     ///
     /// The hash of the created L2 content.
     pub fn build_l2(
-        &mut self,
+        &self,
         source_l1_hashes: Vec<Hash>,
         config: Option<L2BuildConfig>,
     ) -> OpsResult<Hash> {
@@ -60,7 +60,7 @@ where
 
     /// Build L2 with a specific timestamp (for testing).
     pub fn build_l2_with_timestamp(
-        &mut self,
+        &self,
         source_l1_hashes: Vec<Hash>,
         config: Option<L2BuildConfig>,
         timestamp: nodalync_crypto::Timestamp,
@@ -255,7 +255,7 @@ where
     ///
     /// The hash of the merged L2 content.
     pub fn merge_l2(
-        &mut self,
+        &self,
         source_l2_hashes: Vec<Hash>,
         config: Option<L2MergeConfig>,
     ) -> OpsResult<Hash> {
@@ -265,7 +265,7 @@ where
 
     /// Merge L2 with a specific timestamp (for testing).
     pub fn merge_l2_with_timestamp(
-        &mut self,
+        &self,
         source_l2_hashes: Vec<Hash>,
         config: Option<L2MergeConfig>,
         timestamp: nodalync_crypto::Timestamp,
@@ -438,6 +438,7 @@ where
             root_l0l1: merged_roots,
             derived_from: source_l2_hashes.clone(),
             depth: max_depth + 1,
+            attested: false,
         };
 
         // 9. Store content first (compute actual hash from serialized content)
@@ -497,7 +498,7 @@ mod tests {
 
     #[test]
     fn test_build_l2_requires_l1_sources() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Try to build with empty sources
         let result = ops.build_l2(vec![], None);
@@ -506,7 +507,7 @@ mod tests {
 
     #[test]
     fn test_build_l2_rejects_l0_sources() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create L0 content
         let content = b"L0 content";
@@ -520,7 +521,7 @@ mod tests {
 
     #[test]
     fn test_build_l2_from_l1() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create L0 and extract L1 summary (note: extract_l1_summary doesn't create separate L1 manifest)
         let content = b"Some content with interesting facts.";
@@ -539,7 +540,7 @@ mod tests {
 
     #[test]
     fn test_merge_l2_requires_two_sources() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Try to merge with only one source
         let fake_hash = content_hash(b"fake");
@@ -549,7 +550,7 @@ mod tests {
 
     #[test]
     fn test_merge_l2_requires_owned_sources() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Try to merge non-existent sources
         let fake_hash1 = content_hash(b"fake1");
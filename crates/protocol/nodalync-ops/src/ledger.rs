@@ -0,0 +1,516 @@
+//! Economic accounting ledger.
+//!
+//! Every economic event is recorded in the store's double-entry ledger as
+//! it happens:
+//!
+//! | Event | Debit | Credit |
+//! |-------|-------|--------|
+//! | Contract deposit | `contract` | `external` |
+//! | Automatic top-up | `contract` | `external` |
+//! | Contract withdrawal | `external` | `contract` |
+//! | Channel deposit | `channels` | `contract` |
+//! | Channel close | `contract` | `channels` |
+//! | Query paid | `query_spend` | `channels` |
+//! | Revenue received | `channels` | `revenue`, `payable` |
+//! | Fee (synthesis fee as owner, app fee as its recipient) | `channels` | `fees` |
+//! | Settlement payout | `payable` | `contract` |
+//! | Invoice paid | `query_spend` | `channels` |
+//! | Invoice received | `channels` | `revenue` |
+//!
+//! Recording is best-effort: a failed posting is logged and never fails the
+//! operation that caused it. [`NodeOperations::reconcile_ledger`] compares
+//! the ledger against channel and settlement state to surface any drift.
+
+use nodalync_crypto::{Hash, PeerId, Timestamp};
+use nodalync_econ::{calculate_synthesis_fee, distribute_revenue_with_referral, ReferralCut};
+use nodalync_store::{
+    ChannelStore, LedgerAccount, LedgerEntry, LedgerEvent, LedgerPosting, LedgerStore,
+    LedgerTransaction, QueuedDistribution, SettlementQueueStore,
+};
+use nodalync_types::{Amount, ProvenanceEntry};
+use nodalync_valid::Validator;
+use tracing::warn;
+
+use crate::error::OpsResult;
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+/// Header row of the CSV export.
+pub const LEDGER_CSV_HEADER: &str =
+    "transaction_id,timestamp,event,reference,account,debit,credit,balance";
+
+/// Ledger balance of an account compared with the node's actual state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconciliationCheck {
+    /// Account checked.
+    pub account: LedgerAccount,
+    /// Balance according to the ledger.
+    pub ledger_balance: i64,
+    /// Balance according to channel or settlement state.
+    pub actual_balance: i64,
+}
+
+impl ReconciliationCheck {
+    /// Actual balance minus ledger balance.
+    pub fn difference(&self) -> i64 {
+        self.actual_balance - self.ledger_balance
+    }
+
+    /// Whether the ledger agrees with the actual balance.
+    pub fn is_reconciled(&self) -> bool {
+        self.difference() == 0
+    }
+}
+
+/// Result of reconciling the ledger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LedgerReconciliation {
+    /// Total debits across all entries.
+    pub total_debits: Amount,
+    /// Total credits across all entries.
+    pub total_credits: Amount,
+    /// Per-account checks: `channels` against open channel balances,
+    /// `payable` against the settlement queue, and `contract` against the
+    /// settlement contract (when settlement is configured).
+    pub checks: Vec<ReconciliationCheck>,
+}
+
+impl LedgerReconciliation {
+    /// Whether total debits equal total credits.
+    pub fn is_balanced(&self) -> bool {
+        self.total_debits == self.total_credits
+    }
+
+    /// Whether the ledger is balanced and every check agrees.
+    ///
+    /// Activity from before the ledger was introduced shows up as a
+    /// difference.
+    pub fn is_reconciled(&self) -> bool {
+        self.is_balanced() && self.checks.iter().all(ReconciliationCheck::is_reconciled)
+    }
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// List ledger entries oldest first, optionally for one account and
+    /// from a timestamp onwards.
+    pub fn ledger_entries(
+        &self,
+        account: Option<LedgerAccount>,
+        since: Option<Timestamp>,
+    ) -> OpsResult<Vec<LedgerEntry>> {
+        Ok(self.state.ledger.entries(account, since)?)
+    }
+
+    /// Get the current balance of every ledger account.
+    pub fn ledger_balances(&self) -> OpsResult<Vec<(LedgerAccount, i64)>> {
+        LedgerAccount::ALL
+            .into_iter()
+            .map(|account| Ok((account, self.state.ledger.balance(account)?)))
+            .collect()
+    }
+
+    /// Reconcile the ledger against channel and settlement state.
+    pub async fn reconcile_ledger(&self) -> OpsResult<LedgerReconciliation> {
+        let (total_debits, total_credits) = self.state.ledger.totals()?;
+
+        let channels: i64 = self
+            .state
+            .channels
+            .list_open()?
+            .iter()
+            .map(|(_, channel)| channel.my_balance as i64)
+            .sum();
+
+        let me = self.peer_id();
+        let payable: i64 = self
+            .state
+            .settlement
+            .get_pending()?
+            .iter()
+            .filter(|d| d.recipient != me)
+            .map(|d| d.amount as i64)
+            .sum();
+
+        let mut actual = vec![
+            (LedgerAccount::Channels, channels),
+            (LedgerAccount::Payable, payable),
+        ];
+        if let Some(settlement) = self.settlement().cloned() {
+            match settlement.get_balance().await {
+                Ok(balance) => actual.push((LedgerAccount::Contract, balance as i64)),
+                Err(e) => warn!(error = %e, "Failed to get contract balance for reconciliation"),
+            }
+        }
+
+        let checks = actual
+            .into_iter()
+            .map(|(account, actual_balance)| {
+                Ok(ReconciliationCheck {
+                    account,
+                    ledger_balance: self.state.ledger.balance(account)?,
+                    actual_balance,
+                })
+            })
+            .collect::<OpsResult<Vec<_>>>()?;
+
+        Ok(LedgerReconciliation {
+            total_debits,
+            total_credits,
+            checks,
+        })
+    }
+
+    /// Export ledger entries as CSV, oldest first.
+    ///
+    /// One row per entry, with the columns in [`LEDGER_CSV_HEADER`].
+    /// Timestamps are milliseconds since the Unix epoch and amounts are in
+    /// tinybars.
+    pub fn export_ledger_csv(&self, since: Option<Timestamp>) -> OpsResult<String> {
+        let mut csv = String::from(LEDGER_CSV_HEADER);
+        csv.push('\n');
+        for entry in self.state.ledger.entries(None, since)? {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{}\n",
+                entry.transaction_id,
+                entry.timestamp,
+                entry.event,
+                csv_field(entry.reference.as_deref().unwrap_or("")),
+                entry.account,
+                entry.debit,
+                entry.credit,
+                entry.balance,
+            ));
+        }
+        Ok(csv)
+    }
+
+    /// Post a transaction to the ledger, logging failures.
+    ///
+    /// Transactions without postings (zero amounts) are skipped.
+    pub(crate) fn record_ledger(&self, transaction: LedgerTransaction) {
+        if transaction.postings.is_empty() {
+            return;
+        }
+        if let Err(e) = self.state.ledger.post(&transaction) {
+            warn!(event = %transaction.event, error = %e, "Failed to record ledger transaction");
+        }
+    }
+
+    /// Record a transfer of `amount` from one account to another.
+    pub(crate) fn record_ledger_transfer(
+        &self,
+        event: LedgerEvent,
+        reference: impl ToString,
+        from: LedgerAccount,
+        to: LedgerAccount,
+        amount: Amount,
+    ) {
+        self.record_ledger(LedgerTransaction::transfer(
+            event,
+            Some(reference.to_string()),
+            current_timestamp(),
+            to,
+            from,
+            amount,
+        ));
+    }
+
+    /// Record our deposit into a newly opened channel.
+    pub(crate) fn record_channel_deposit(&self, channel_id: &Hash, amount: Amount) {
+        self.record_ledger_transfer(
+            LedgerEvent::ChannelDeposit,
+            channel_id,
+            LedgerAccount::Contract,
+            LedgerAccount::Channels,
+            amount,
+        );
+    }
+
+    /// Record our final balance returning from a closed channel.
+    pub(crate) fn record_channel_close(&self, channel_id: &Hash, amount: Amount) {
+        self.record_ledger_transfer(
+            LedgerEvent::ChannelClose,
+            channel_id,
+            LedgerAccount::Channels,
+            LedgerAccount::Contract,
+            amount,
+        );
+    }
+
+    /// Record a settled batch paying out the queued distributions owed to
+    /// other contributors.
+    pub(crate) fn record_settlement_payout(
+        &self,
+        reference: &str,
+        distributions: &[QueuedDistribution],
+    ) {
+        let me = self.peer_id();
+        let amount = distributions
+            .iter()
+            .filter(|d| d.recipient != me)
+            .map(|d| d.amount)
+            .sum();
+        self.record_ledger_transfer(
+            LedgerEvent::SettlementPayout,
+            reference,
+            LedgerAccount::Contract,
+            LedgerAccount::Payable,
+            amount,
+        );
+    }
+
+    /// Record revenue received in a channel for a query of content owned
+    /// by `owner` with the given root provenance.
+    ///
+    /// Our own share is income, with fees booked separately: the synthesis
+    /// fee when we own the content and the app fee when we receive it. The
+    /// rest, including any referral cut, is payable to the other recipients.
+    /// Returns the payable amount.
+    pub(crate) fn record_query_revenue(
+        &self,
+        reference: impl ToString,
+        amount: Amount,
+        owner: &PeerId,
+        provenance: &[ProvenanceEntry],
+        referral: Option<&ReferralCut>,
+    ) -> Amount {
+        let me = self.peer_id();
+        let app_fee = self.config.app_fee;
+        let ours: Amount =
+            distribute_revenue_with_referral(amount, owner, provenance, app_fee.as_ref(), referral)
+                .iter()
+                .filter(|d| d.recipient == me)
+                .map(|d| d.amount)
+                .sum();
+        let app_fee_amount = app_fee.map_or(0, |fee| fee.amount(amount));
+        let referral_amount = referral.map_or(0, |cut| cut.amount(amount));
+        let mut fee = 0;
+        if *owner == me {
+            fee += calculate_synthesis_fee(amount - app_fee_amount - referral_amount);
+        }
+        if app_fee.is_some_and(|fee| fee.recipient == me) {
+            fee += app_fee_amount;
+        }
+        let fee = fee.min(ours);
+        let payable = amount - ours;
+
+        let reference = reference.to_string();
+        let timestamp = current_timestamp();
+        self.record_ledger(
+            LedgerTransaction::new(
+                LedgerEvent::RevenueReceived,
+                Some(reference.clone()),
+                timestamp,
+            )
+            .with_posting(LedgerPosting::debit(LedgerAccount::Channels, amount - fee))
+            .with_posting(LedgerPosting::credit(LedgerAccount::Revenue, ours - fee))
+            .with_posting(LedgerPosting::credit(LedgerAccount::Payable, payable)),
+        );
+        self.record_ledger(LedgerTransaction::transfer(
+            LedgerEvent::Fee,
+            Some(reference),
+            timestamp,
+            LedgerAccount::Channels,
+            LedgerAccount::Fees,
+            fee,
+        ));
+
+        payable
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key, Signature};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_test_utils::*;
+    use nodalync_types::{Metadata, Payment, Visibility};
+    use nodalync_wire::QueryRequestPayload;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let config = NodeStateConfig::new(temp_dir.path());
+        let state = NodeState::open(config).unwrap();
+        let (_, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+        let ops = DefaultNodeOperations::with_defaults(state, peer_id);
+        (ops, temp_dir)
+    }
+
+    fn balance(balances: &[(LedgerAccount, i64)], account: LedgerAccount) -> i64 {
+        balances
+            .iter()
+            .find(|(a, _)| *a == account)
+            .map(|(_, b)| *b)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_paid_query_is_recorded_and_reconciles() {
+        let mock = MockSettlement::new().with_balance(0);
+        let (ops, _temp) = create_test_ops_with_settlement(Arc::new(mock));
+
+        ops.wallet_deposit(10_000).await.unwrap();
+
+        let hash = ops
+            .create_content(b"ledger content", Metadata::new("Ledger", 14))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 1_000)
+            .await
+            .unwrap();
+
+        let (_, public_key) = generate_identity();
+        let requester = peer_id_from_public_key(&public_key);
+        let channel_id = content_hash(b"ledger-channel");
+        ops.accept_payment_channel(&channel_id, &requester, 5_000, 2_000)
+            .unwrap();
+
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        let request = QueryRequestPayload {
+            hash,
+            query: None,
+            payment: Some(Payment::new(
+                content_hash(b"payment"),
+                channel_id,
+                1_000,
+                manifest.owner,
+                hash,
+                manifest.provenance.root_l0l1.clone(),
+                current_timestamp(),
+                Signature::from_bytes([0u8; 64]),
+            )),
+            version_spec: None,
+            payment_nonce: 1,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        };
+        ops.handle_query_request(&requester, &request)
+            .await
+            .unwrap();
+
+        // We own the only root, so all revenue is ours
+        let balances = ops.ledger_balances().unwrap();
+        let fee = calculate_synthesis_fee(1_000) as i64;
+        assert_eq!(balance(&balances, LedgerAccount::External), 10_000);
+        assert_eq!(balance(&balances, LedgerAccount::Contract), 8_000);
+        assert_eq!(balance(&balances, LedgerAccount::Channels), 3_000);
+        assert_eq!(balance(&balances, LedgerAccount::Fees), fee);
+        assert_eq!(balance(&balances, LedgerAccount::Revenue), 1_000 - fee);
+        assert_eq!(balance(&balances, LedgerAccount::Payable), 0);
+
+        let events: Vec<LedgerEvent> = ops
+            .ledger_entries(Some(LedgerAccount::Channels), None)
+            .unwrap()
+            .iter()
+            .map(|e| e.event)
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                LedgerEvent::ChannelDeposit,
+                LedgerEvent::RevenueReceived,
+                LedgerEvent::Fee
+            ]
+        );
+
+        let reconciliation = ops.reconcile_ledger().await.unwrap();
+        assert!(reconciliation.is_balanced());
+        let channels = reconciliation
+            .checks
+            .iter()
+            .find(|c| c.account == LedgerAccount::Channels)
+            .unwrap();
+        assert!(channels.is_reconciled());
+        // The mock contract balance doesn't track channel deposits
+        let contract = reconciliation
+            .checks
+            .iter()
+            .find(|c| c.account == LedgerAccount::Contract)
+            .unwrap();
+        assert_eq!(contract.actual_balance, 10_000);
+        assert_eq!(contract.difference(), 2_000);
+        assert!(!reconciliation.is_reconciled());
+    }
+
+    #[test]
+    fn test_revenue_split_with_other_contributors() {
+        let (ops, _temp) = create_test_ops();
+        let (_, public_key) = generate_identity();
+        let other = peer_id_from_public_key(&public_key);
+        let me = ops.peer_id();
+        let provenance = vec![
+            ProvenanceEntry::new(content_hash(b"mine"), me, Visibility::Shared),
+            ProvenanceEntry::new(content_hash(b"theirs"), other, Visibility::Shared),
+        ];
+
+        let payable = ops.record_query_revenue("payment", 1_000, &me, &provenance, None);
+        assert!(payable > 0);
+        ops.record_ledger_transfer(
+            LedgerEvent::SettlementPayout,
+            "0.0.1@2",
+            LedgerAccount::Contract,
+            LedgerAccount::Payable,
+            payable,
+        );
+
+        let balances = ops.ledger_balances().unwrap();
+        let income =
+            balance(&balances, LedgerAccount::Revenue) + balance(&balances, LedgerAccount::Fees);
+        assert_eq!(income + payable as i64, 1_000);
+        assert_eq!(balance(&balances, LedgerAccount::Channels), 1_000);
+        assert_eq!(balance(&balances, LedgerAccount::Payable), 0);
+        assert_eq!(
+            balance(&balances, LedgerAccount::Contract),
+            -(payable as i64)
+        );
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let (ops, _temp) = create_test_ops();
+        ops.record_ledger_transfer(
+            LedgerEvent::ContractDeposit,
+            "tx,\"1\"",
+            LedgerAccount::External,
+            LedgerAccount::Contract,
+            500,
+        );
+        ops.record_ledger_transfer(
+            LedgerEvent::ContractWithdrawal,
+            "tx2",
+            LedgerAccount::Contract,
+            LedgerAccount::External,
+            0,
+        );
+
+        let csv = ops.export_ledger_csv(None).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], LEDGER_CSV_HEADER);
+        assert!(lines[1].ends_with(",contract_deposit,\"tx,\"\"1\"\"\",contract,500,0,500"));
+        assert!(lines[2].ends_with(",contract_deposit,\"tx,\"\"1\"\"\",external,0,500,500"));
+    }
+}
@@ -8,15 +8,66 @@
 //!
 //! - [`error`] - Operation error types
 //! - [`config`] - Configuration for channels and operations
+//! - [`config_file`] - Configuration validation and TOML loading
 //! - [`extraction`] - L1 mention extraction
-//! - [`ops`] - Main Operations trait definition
+//! - [`ops`] - Operations traits, split by capability
 //! - [`node_ops`] - NodeOperations implementation
+//! - [`advertise`] - Capability advertisement in peer info, and gating on peers' capabilities
 //! - [`content`] - Content operations (create, update, derive, reference)
 //! - [`query`] - Query operations (preview, query, get_versions)
-//! - [`publish`] - Publish operations (publish, unpublish, visibility, access)
+//! - [`chunked`] - Chunk-verified downloads of free content from multiple providers
+//! - [`challenge`] - Proof-of-possession challenges before serving paid queries
+//! - [`retry`] - Structured query errors, busy refusals, and retrying with alternative providers
+//! - [`publish`] - Publish operations (publish, schedule, unpublish, visibility, access)
+//! - [`collection`] - Curated collections sold as bundles
+//! - [`attribution`] - Signed attribution certificates for derived content
+//! - [`attestation`] - On-chain attestation of content provenance
+//! - [`bond`] - Publisher bonds: posting, checking before payment, slashing
+//! - [`fraud`] - Fraud proofs against providers serving the wrong content
 //! - [`channel`] - Channel operations (open, accept, close, dispute)
+//! - [`dispute`] - Dispute tracking and deadlines, evidence packages and automatic counter-disputes
+//! - [`rebalance`] - Channel skew monitoring and rebalance planning
 //! - [`settlement`] - Settlement operations (trigger_settlement)
+//! - [`top_up`] - Automatic settlement balance top-ups
+//! - [`webhook`] - Signed webhooks for economic and content events, with retries
+//! - [`notification`] - Daily and weekly digests of earnings and activity
+//! - [`wallet`] - Wallet operations (balances, deposits, earnings)
+//! - [`ledger`] - Double-entry economic ledger, reconciliation, CSV export
+//! - [`library`] - The library of content we bought, re-opened from the cache
+//! - [`entitlement`] - Re-downloads of bought content with the purchase receipt
+//! - [`invoice`] - Signed invoices: create, send, fetch, pay
+//! - [`capability`] - Capability tokens for sharing unpublished content
+//! - [`group`] - Signed group membership lists referenced from access control
+//! - [`tombstone`] - Owner-signed tombstones withdrawing content from the network
+//! - [`revocation`] - Key-compromise revocation certificates and revoked-key checks
+//! - [`moderation`] - Content reports and the moderation review queue
+//! - [`did`] - `did:key` identities: DID document export, resolution and verification
+//! - [`analytics`] - Per-content access analytics for publishers
+//! - [`pricing`] - Price suggestions for new content, and current prices under dynamic pricing rules
+//! - [`section`] - Sections of content priced, queried and verified on their own
+//! - [`metering`] - Metered reads paying per unit as content arrives, with early cut-off
+//! - [`trial`] - Free trial reads of paid content, counted per requester
+//! - [`quota`] - Daily query and byte quotas per requester, per content and node-wide
+//! - [`referral`] - Referral cuts paid to discovery nodes that refer paying queries
+//! - [`popularity`] - Decaying content popularity and cache prewarming
+//! - [`announce_filter`] - Spam and abuse filtering for incoming announcements
+//! - [`ingest`] - Batched storage of gossiped announcements with backpressure
+//! - [`trust`] - Trust policy screening of content fetched from peers
+//! - [`recommend`] - Content recommendations from the L2 graph and query history
+//! - [`schema`] - Metadata schemas for structured fields
+//! - [`search`] - Federated search fan-out and result merging
+//! - [`tags`] - Tag registry listing and autocomplete
+//! - [`snapshot`] - Signed state snapshots and fast sync for fresh nodes
+//! - [`storefront`] - Signed publisher catalogs stored in the DHT
+//! - [`stats`] - Node statistics gathered across subsystems
+//! - [`sync`] - Encrypted sync bundles moving identity and channel state between devices
+//! - [`replica`] - Read-only replicas serving a primary's catalog under a signed delegation
+//! - [`version_chain`] - Version chain gap and fork detection, and repair from the owner
+//! - [`fork`] - Flagging versions the owner forked, and resolving them by policy
 //! - [`handlers`] - Incoming message handlers
+//! - [`standing_order`] - Standing orders buying announced content automatically, into a local inbox
+//! - [`replay`] - Replaying recorded wire messages through the handlers
+//! - [`trace`] - Request IDs correlating tracing spans across nodes
 //! - [`helpers`] - Utility functions
 //!
 //! # Example
@@ -63,13 +114,34 @@
 //! - **preview**: Get content metadata and L1 summary
 //! - **query**: Retrieve full content with payment
 //! - **get_versions**: List all versions of content
+//! - **verify_version_chain**: Find gaps and forks in a version chain
+//! - **repair_version_chain**: Fetch missing version manifests from the owner
+//! - **current_version**: Resolve the current version of a lineage, picking
+//!   among forks by the fork policy
+//! - **download_chunked**: Fetch large free content chunk by chunk from
+//!   every provider, re-fetching corrupt chunks from another provider
+//! - **library** / **search_library**: List and search the content we
+//!   bought, recorded by each paid query
+//! - **open_from_library**: Re-open bought content from the cache
+//! - **redownload**: Fetch bought content again from its provider, presenting
+//!   the purchase receipt instead of paying
 //!
 //! ## Visibility Operations (§7.1.3)
 //!
 //! - **publish**: Make content discoverable with price
 //! - **unpublish**: Make content private
+//! - **schedule_publish**: Publish content under embargo until a given time
+//! - **publish_scheduled_content**: Announce scheduled content that is due
 //! - **set_visibility**: Change visibility level
 //! - **set_access**: Configure access control
+//! - **set_preview_policy**: Redact sections and patterns from the summary
+//!   peers see before paying
+//! - **mint_capability**: Sign an expiring token granting access to one
+//!   piece of content, optionally bound to a peer, without publishing it
+//! - **query_with_capability**: Query content shared with us by token
+//! - **create_group** / **update_group**: Sign a membership list whose
+//!   members pass Unlisted access checks on content referencing the group
+//! - **fetch_group**: Cache another owner's group, refreshed after a TTL
 //!
 //! ## Channel Operations (§7.3)
 //!
@@ -78,10 +150,114 @@
 //! - **update_channel**: Record payment in channel
 //! - **close_channel**: Close channel and settle
 //! - **dispute_channel**: Submit dispute for channel
+//! - **close_all_payment_channels**: Close every open channel with bounded concurrency
 //!
 //! ## Settlement Operations (§7.5)
 //!
 //! - **trigger_settlement**: Create and submit settlement batch
+//! - **check_top_up**: Deposit into the settlement contract when its balance
+//!   runs low, within a daily cap
+//!
+//! ## Wallet Operations
+//!
+//! - **wallet_summary**: Contract/account balances, pending queue, total earnings
+//! - **wallet_deposit** / **wallet_withdraw**: Move funds via the `Settlement` trait
+//! - **recent_wallet_transactions**: Locally recorded on-chain transaction history
+//! - **content_earnings**: Per-content revenue for owned content
+//! - **ledger_entries** / **ledger_balances**: Double-entry records of every
+//!   economic event with running balances
+//! - **reconcile_ledger**: Check the ledger against channel and settlement state
+//! - **export_ledger_csv**: Export ledger entries for accounting
+//! - **create_invoice** / **send_invoice** / **fetch_invoice** / **pay_invoice**:
+//!   Signed payment requests paid through the payment channel with the payee
+//! - **list_invoices** / **get_invoice**: Issued and received invoices with
+//!   their payment status
+//!
+//! ## Analytics
+//!
+//! - **create_collection**: Bundle local content into an ordered, optionally
+//!   priced collection; bundle revenue is split across items by weight
+//! - **content_stats**: Previews, queries, unique consumers, daily revenue,
+//!   and reported usage for a piece of local content
+//! - **report_usage**: Send an anonymized usage report (bytes read, context
+//!   size, rating) to the publisher of queried content, if both sides opt in
+//! - **suggest_price**: Suggest a price range for new content from similar
+//!   announced and published content and its demand
+//! - **set_content_pricing** / **current_price** / **quote_price**: Surge and
+//!   Dutch auction pricing rules, and the price they charge right now
+//! - **set_content_sections** / **set_section_prices** / **query_section**:
+//!   Sell sections of content at their own prices, and buy just one
+//! - **set_content_metering** / **query_metered**: Read content unit by unit,
+//!   paying for each, and stop once there is enough
+//! - **set_content_trial** / **query_trial**: Offer a few free reads of paid
+//!   content to each requester, and take one
+//! - **set_content_quota** / **quota_usage** / **reset_quota**: Limit each
+//!   requester's queries or bytes per day, see who is using them, and lift
+//!   a requester's usage
+//! - **set_content_referral** / **query_referred**: Offer a cut of each
+//!   payment to the node that referred the query, and name one
+//! - **stats**: One snapshot of content, storage, peers, channels, pending
+//!   settlement, earnings, manifest index hit rate and uptime
+//!
+//! ## Maintenance
+//!
+//! - **scrub_content**: Re-hash stored blobs against their manifests,
+//!   quarantine mismatches, and restore them from the cache or a provider
+//! - **register_schema**: Cache a metadata schema that structured fields
+//!   can reference by URI
+//! - **enforce_retention** / **retention_status**: Expire announcements,
+//!   cached content, peer records, channel history and analytics by
+//!   per-category TTLs
+//! - **probe_clock_skew**: Estimate the local clock's offset from peers'
+//!   pong timestamps and correct message timestamps by it
+//! - **replay_entry**: Feed a recorded wire message back through the
+//!   handlers, at the time it was received
+//! - **prewarm_cache** / **popular_content**: Hold hot manifests in memory,
+//!   keep popular cached content, and fetch popular free content ahead of
+//!   demand
+//! - **add_standing_order** / **run_standing_orders**: Buy content announced
+//!   under a tag, within a price, budget and publisher reputation, into a
+//!   local inbox
+//!
+//! ## Discovery
+//!
+//! - **recommended_content**: Announcements ranked against the user's L2
+//!   entity graphs and query history
+//! - **fast_sync**: Fetch a signed snapshot of a well-connected peer's
+//!   announcement index and peer list, import it, and dial new peers
+//! - **export_snapshot** / **import_snapshot**: Take and apply snapshots
+//! - **publish_storefront** / **fetch_storefront**: Sign our catalog into
+//!   the DHT under our peer ID, and fetch a publisher's to browse
+//!
+//! ## Devices
+//!
+//! - **export_sync_bundle** / **import_sync_bundle**: Encrypt the identity,
+//!   channels, receipts and settlement accounts for another device, and
+//!   merge such a bundle, refusing channels both devices advanced
+//! - **push_sync_bundle** / **pull_sync_bundle**: Leave a bundle with a
+//!   peer that holds them, and fetch it on another device
+//!
+//! ## Replicas
+//!
+//! - **delegate_replica** / **export_replica_catalog**: Sign a delegation
+//!   letting another node serve our catalog, and export the catalog for it
+//! - **import_replica_catalog**: Store a primary's catalog to serve,
+//!   registering its settlement account
+//! - **announce_replicated_content**: Announce replicated content with the
+//!   primary's delegation attached
+//!
+//! ## Webhooks
+//!
+//! - **deliver_webhooks**: Post queued event notifications, signed with
+//!   each endpoint's secret, retrying failures with backoff
+//! - **webhook_deliveries**: Delivery history and status
+//!
+//! ## Activity Digests
+//!
+//! - **activity_digest**: Summarize earnings, top content, new consumers
+//!   and failed settlements over a period
+//! - **send_due_digests**: Send digests of periods that ended through the
+//!   registered notifiers
 //!
 //! # Design Notes
 //!
@@ -111,6 +287,9 @@
 //!
 //! The query operation checks for an existing channel and auto-opens one
 //! if sufficient balance is available. This simplifies the query flow.
+//! Auto-opens go through `auto_open_payment_channel`, which enforces the
+//! `AutoOpenPolicy` in `OpsConfig` (deposit cap, channel count, provider
+//! reputation, daily limit, and an optional approval callback).
 //!
 //! ## Network Integration
 //!
@@ -119,20 +298,83 @@
 //! operations will use P2P networking; otherwise they fall back to local-only mode.
 
 // Module declarations
+pub mod advertise;
+pub mod analytics;
+pub mod announce_filter;
+pub mod attestation;
+pub mod attribution;
+pub mod availability;
+pub mod bond;
+pub mod capability;
+pub mod challenge;
 pub mod channel;
+pub mod chunked;
+pub mod clock;
+pub mod close_batch;
+pub mod collection;
 pub mod config;
+pub mod config_file;
 pub mod content;
+pub mod did;
+pub mod dispute;
+pub mod encryption;
+pub mod entitlement;
 pub mod error;
+pub mod events;
 pub mod extraction;
+pub mod fork;
+pub mod fraud;
+pub mod group;
 pub mod handlers;
 pub mod helpers;
+pub mod ingest;
+pub mod invoice;
 pub mod l2;
+pub mod ledger;
+pub mod library;
+pub mod metering;
+pub mod moderation;
 pub mod node_ops;
+pub mod notification;
 pub mod ops;
 pub mod peer_key_lookup;
+pub mod popularity;
+pub mod pricing;
 pub mod publish;
+pub mod qos;
 pub mod query;
+pub mod quota;
+pub mod rebalance;
+pub mod recommend;
+pub mod redaction;
+pub mod referral;
+pub mod relay;
+pub mod replay;
+pub mod replica;
+pub mod retention;
+pub mod retry;
+pub mod revocation;
+pub mod schema;
+pub mod scrub;
+pub mod search;
+pub mod section;
 pub mod settlement;
+pub mod snapshot;
+pub mod standing_order;
+pub mod stats;
+pub mod storefront;
+pub mod sync;
+pub mod tags;
+pub mod tenant;
+pub mod tombstone;
+pub mod top_up;
+pub mod trace;
+pub mod trial;
+pub mod trust;
+pub mod usage;
+pub mod version_chain;
+pub mod wallet;
+pub mod webhook;
 
 // Re-export main types at crate root
 
@@ -143,18 +385,134 @@ pub use error::{CloseResult, OpsError, OpsResult};
 pub use nodalync_net::{Network, NetworkError, NetworkEvent};
 
 // Configuration
-pub use config::{ChannelConfig, OpsConfig};
+pub use config::{
+    AnalyticsConfig, AnnouncementFilterConfig, AnnouncementIngestConfig, AutoOpenApprover,
+    AutoOpenPolicy, AutoOpenRequest, AvailabilityConfig, BondConfig, ChannelConfig,
+    ClockSkewConfig, CloseBatchConfig, DisputeConfig, EntitlementConfig, ForkPolicy,
+    FraudProofConfig, ModerationConfig, NotificationConfig, OpsConfig, PopularityConfig, QosConfig,
+    QueryChallengeConfig, QueryLimitConfig, QueryRetryConfig, RebalanceConfig,
+    RecommendationConfig, RetentionConfig, SearchConfig, SnapshotConfig, StandingOrderConfig,
+    StorefrontConfig, SyncConfig, TopUpConfig, TrustPolicy, TrustWeights, UsageReportConfig,
+    WebhookConfig, WebhookEndpoint, WebhookEvent,
+};
+
+// Analytics types
+pub use analytics::{ContentStats, RevenueBucket, UsageStats};
+
+// Announcement filter types
+pub use announce_filter::AnnouncementFilterStats;
+
+// Announcement ingestion types
+pub use ingest::AnnouncementIngestStats;
+
+// Attestation types
+pub use attestation::AttestationStatus;
+
+// Clock skew types
+pub use clock::ClockSkewProbe;
+
+// Peer latency probe types
+pub use qos::LatencyProbe;
+
+// Content availability types
+pub use availability::AvailabilityReport;
+
+// Batched close types
+pub use close_batch::CloseBatchReport;
+
+// DID types
+pub use did::DidResolution;
+
+// Event bus
+pub use events::{ChannelCloseStatus, OpsEvent};
 
 // Extraction
 pub use extraction::{L1Extractor, RuleBasedExtractor};
 
 // Operations trait and implementation
 pub use node_ops::{current_timestamp, DefaultNodeOperations, NodeOperations};
-pub use ops::{Operations, PreviewResponse, QueryResponse};
+pub use ops::{
+    ChannelOps, ContentOps, Operations, OpsContext, PreviewResponse, PublishOps, QueryOps,
+    QueryResponse, SettlementOps,
+};
+
+// Popularity types
+pub use popularity::PrewarmReport;
+
+// Standing order types
+pub use standing_order::StandingOrderReport;
+
+// Tenant types
+pub use tenant::{TenantReport, TENANT_KEY_PREFIX};
 
 // Query types
 pub use query::{NetworkSearchResult, SearchSource};
 
+// Ledger types
+pub use ledger::{LedgerReconciliation, ReconciliationCheck, LEDGER_CSV_HEADER};
+
+// Re-export rebalance types
+pub use rebalance::{ChannelSkew, RebalanceAction, RebalanceOutcome, RebalancePlan};
+
+// Recommendation types
+pub use recommend::Recommendation;
+
+// Replay types
+pub use replay::ReplayOutcome;
+
+// Retention types
+pub use retention::{RetentionPurge, RetentionStatus};
+
+// Section types
+pub use section::{heading_sections, SectionResponse};
+
+// Metering types
+pub use metering::{MeteredReceipt, MeteredResponse};
+
+// Trial types
+pub use trial::TrialResponse;
+
+// Scrub types
+pub use scrub::{ScrubIssue, ScrubOutcome, ScrubReport};
+
+// Snapshot types
+pub use snapshot::SnapshotImport;
+
+// Node statistics
+pub use stats::{ContentCounts, NodeStats};
+
+// Replica types
+pub use replica::ReplicaImport;
+
+// Cross-device sync types
+pub use sync::{ChannelConflict, SyncAccount, SyncBundle, SyncChannel, SyncImport};
+
+// Trust policy types
+pub use trust::{TrustCheck, TrustEvaluation, TrustFinding, TrustOutcome};
+
+// Top-up types
+pub use top_up::TopUpOutcome;
+
+// Request correlation
+pub use trace::{current_request_id, new_request_id, with_request_id};
+
+// Referral attribution
+pub use referral::with_referrer;
+
+// Version chain types
+pub use version_chain::{VersionChainRepair, VersionChainReport, VersionFork, VersionGap};
+
+// Wallet types
+pub use wallet::{ContentEarnings, WalletSummary};
+
+// Activity digest types
+pub use notification::{last_complete_period, ActivityDigest, Notifier, TopContent};
+
+// Webhook types
+pub use webhook::{
+    sign_webhook, verify_webhook, WebhookDeliveryReport, WebhookRequest, WebhookTransport,
+};
+
 // Helper functions
 pub use helpers::{
     generate_channel_id, generate_payment_id, is_queryable_by, merge_provenance_entries,
@@ -165,7 +523,10 @@ pub use helpers::{
 pub use peer_key_lookup::PeerStoreKeyLookup;
 
 // Channel payment helpers
-pub use channel::{create_signed_payment, create_signed_payment_for_manifest, sign_payment};
+pub use channel::{
+    create_signed_payment, create_signed_payment_for_manifest, sign_payment, ChannelRecovery,
+    NonceReconciliation,
+};
 
 #[cfg(test)]
 mod tests {
@@ -190,7 +551,7 @@ mod tests {
     /// Integration test: Full content lifecycle
     #[tokio::test]
     async fn test_content_lifecycle() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create content
         let content = b"Original content";
@@ -225,7 +586,7 @@ mod tests {
     /// Integration test: Derive content from sources
     #[test]
     fn test_derive_content() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create sources
         let source1 = b"First source document";
@@ -251,7 +612,7 @@ mod tests {
     /// Integration test: Channel operations
     #[tokio::test]
     async fn test_channel_operations() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         let (_, pk) = generate_identity();
         let peer = peer_id_from_public_key(&pk);
@@ -291,7 +652,7 @@ mod tests {
     /// is NEVER delivered without on-chain settlement confirmation.
     #[tokio::test]
     async fn test_paid_content_requires_settlement() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create and publish content
         let content = b"Paid content";
@@ -324,9 +685,17 @@ mod tests {
         let request = nodalync_wire::QueryRequestPayload {
             hash,
             query: None,
-            payment,
+            payment: Some(payment),
             version_spec: None,
             payment_nonce: 1,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
         };
 
         // Without settlement configured, paid queries MUST be rejected
@@ -342,7 +711,7 @@ mod tests {
     /// Integration test: L1 extraction
     #[test]
     fn test_l1_extraction() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         let content = b"Apple announced new products. We found significant improvements in battery life. According to researchers, the data shows a 50% increase.";
         let meta = Metadata::new("Tech News", content.len() as u64);
@@ -0,0 +1,162 @@
+//! The library of content we bought.
+//!
+//! Every paid query that succeeds is recorded in the library with the
+//! content's title, the price paid, the provider and its payment receipt:
+//!
+//! - [`library`](NodeOperations::library) lists purchases, most recent first
+//! - [`search_library`](NodeOperations::search_library) finds them by title
+//! - [`open_from_library`](NodeOperations::open_from_library) re-opens bought
+//!   content from the cache, without paying again
+//!
+//! Buying content again replaces its entry. Free queries and our own
+//! content aren't recorded, and neither are trial, section or metered
+//! reads, which don't buy the whole content.
+
+use nodalync_crypto::Hash;
+use nodalync_store::{CacheStore, LibraryEntry, LibraryStore};
+use nodalync_valid::Validator;
+use tracing::warn;
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+use crate::ops::QueryResponse;
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// List the content we bought, most recently bought first.
+    pub fn library(&self) -> OpsResult<Vec<LibraryEntry>> {
+        Ok(self.state.library.list()?)
+    }
+
+    /// Find bought content whose title contains `text`, ignoring case.
+    pub fn search_library(&self, text: &str) -> OpsResult<Vec<LibraryEntry>> {
+        Ok(self.state.library.search(text)?)
+    }
+
+    /// Re-open bought content from the cache.
+    ///
+    /// Fails with `NotFound` if the content isn't in the library, and with
+    /// `InvalidOperation` if it was evicted from the cache and must be
+    /// queried again.
+    pub fn open_from_library(&self, hash: &Hash) -> OpsResult<(LibraryEntry, Vec<u8>)> {
+        let entry = self
+            .state
+            .library
+            .get(hash)?
+            .ok_or(OpsError::NotFound(*hash))?;
+        let cached = self.state.cache.get(hash)?.ok_or_else(|| {
+            OpsError::invalid_operation("content is no longer cached; query it again")
+        })?;
+        Ok((entry, cached.content))
+    }
+
+    /// Record a successful query in the library if it was paid for.
+    pub(crate) fn record_purchase(&self, response: &QueryResponse) {
+        let manifest = &response.manifest;
+        if response.receipt.amount == 0 || manifest.owner == self.peer_id() {
+            return;
+        }
+
+        let entry = LibraryEntry {
+            hash: manifest.hash,
+            title: manifest.metadata.title.clone(),
+            content_type: manifest.content_type,
+            price_paid: response.receipt.amount,
+            provider: manifest.owner,
+            purchased_at: current_timestamp(),
+            receipt: response.receipt.clone(),
+        };
+        if let Err(e) = self.state.library.record(&entry) {
+            warn!(hash = %manifest.hash, error = %e, "Failed to record purchase in library");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OpsConfig;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::{ManifestStore, NodeState, NodeStateConfig};
+    use nodalync_types::{Amount, Metadata, Visibility};
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops = DefaultNodeOperations::with_config(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default(),
+        );
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    /// Store content owned by another peer locally, priced at `price`.
+    fn foreign_content(ops: &DefaultNodeOperations, title: &str, price: Amount) -> Hash {
+        let content = format!("Content of {}", title);
+        let hash = ops
+            .create_content(
+                content.as_bytes(),
+                Metadata::new(title, content.len() as u64),
+            )
+            .unwrap();
+        let mut manifest = ops.state.manifests.load(&hash).unwrap().unwrap();
+        let (_, other_pk) = generate_identity();
+        manifest.owner = peer_id_from_public_key(&other_pk);
+        manifest.visibility = Visibility::Shared;
+        manifest.economics.price = price;
+        ops.state.manifests.update(&manifest).unwrap();
+        hash
+    }
+
+    #[tokio::test]
+    async fn test_paid_queries_fill_library() {
+        let (ops, _temp) = create_test_ops();
+        let owls = foreign_content(&ops, "Owls of the North", 10);
+        let moss = foreign_content(&ops, "Moss and Lichen", 5);
+        let free = foreign_content(&ops, "Free Owls", 0);
+        let own = ops
+            .create_content(b"Our own owls", Metadata::new("Our Owls", 12))
+            .unwrap();
+
+        let response = ops.query_content(&owls, 10, None).await.unwrap();
+        ops.query_content(&moss, 5, None).await.unwrap();
+        ops.query_content(&free, 0, None).await.unwrap();
+        ops.query_content(&own, 0, None).await.unwrap();
+
+        // Only paid queries of others' content are recorded
+        let library = ops.library().unwrap();
+        assert_eq!(library.len(), 2);
+        let entry = library.iter().find(|e| e.hash == owls).unwrap();
+        assert_eq!(entry.title, "Owls of the North");
+        assert_eq!(entry.price_paid, 10);
+        assert_eq!(entry.provider, response.manifest.owner);
+        assert_eq!(entry.receipt, response.receipt);
+
+        let found = ops.search_library("OWLS").unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].hash, owls);
+
+        // Re-opened from the cache until it is evicted
+        let (entry, content) = ops.open_from_library(&owls).unwrap();
+        assert_eq!(entry.hash, owls);
+        assert_eq!(content, response.content);
+        ops.state.cache.remove(&owls).unwrap();
+        assert!(matches!(
+            ops.open_from_library(&owls),
+            Err(OpsError::InvalidOperation(_))
+        ));
+        assert!(matches!(
+            ops.open_from_library(&free),
+            Err(OpsError::NotFound(_))
+        ));
+    }
+}
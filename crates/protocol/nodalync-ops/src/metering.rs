@@ -0,0 +1,356 @@
+//! Metered delivery: content read unit by unit, paying as it arrives.
+//!
+//! An owner can split paid content into fixed-size units, recording the
+//! [`chunk_hash`](nodalync_crypto::chunk_hash) of each in
+//! `metadata.metering`. The price is spread evenly across the units (see
+//! `Manifest::unit_price`), so reading all of them costs the same as
+//! buying the content whole. A reader that only needs the start, such as an
+//! agent looking for one answer, stops once it has enough:
+//!
+//! - Each unit is its own query, carrying a channel payment for just that
+//!   unit, which the provider settles and distributes like any other
+//! - The reader checks every unit against its hash before paying for the
+//!   next, so a bad provider costs at most one unit
+//! - The [`MeteredReceipt`] reconciles what was paid with the full price,
+//!   keeping the provider's receipt for each unit
+//!
+//! Units carry no delivery commitment, which only covers whole content. A
+//! partial read is not cached; one that reaches the last unit is checked
+//! against the content hash and cached like any other query.
+
+use nodalync_crypto::{Hash, Signature};
+use nodalync_store::{CacheStore, CachedContent, ChannelStore, ContentStore, ManifestStore};
+use nodalync_types::{Amount, ChunkTree, ContentType, Manifest};
+use nodalync_valid::{validate_content, validate_metadata, Validator};
+use nodalync_wire::{PaymentReceipt, QueryRequestPayload};
+use tracing::warn;
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::helpers::verify_content_hash;
+use crate::node_ops::{current_timestamp, NodeOperations};
+use crate::referral::current_referrer;
+use crate::trace::request_id_or_new;
+
+/// What a metered read paid, reconciled against the full price.
+#[derive(Debug, Clone)]
+pub struct MeteredReceipt {
+    /// Units paid for and delivered.
+    pub units: u32,
+    /// Units the content is split into.
+    pub total_units: u32,
+    /// Total paid across the units.
+    pub amount: Amount,
+    /// What the whole content costs.
+    pub full_price: Amount,
+    /// The provider's receipt for each unit, in order.
+    pub unit_receipts: Vec<PaymentReceipt>,
+}
+
+impl MeteredReceipt {
+    /// Whether every unit was read.
+    pub fn is_complete(&self) -> bool {
+        self.units == self.total_units
+    }
+
+    /// How much less than the full price the read cost.
+    pub fn saved(&self) -> Amount {
+        self.full_price.saturating_sub(self.amount)
+    }
+}
+
+/// Response from a metered read.
+#[derive(Debug, Clone)]
+pub struct MeteredResponse {
+    /// The units read, in order: the start of the content.
+    pub content: Vec<u8>,
+    /// The content manifest.
+    pub manifest: Manifest,
+    /// What was paid.
+    pub receipt: MeteredReceipt,
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Offer owned content for metered reading in units of `unit_size`
+    /// bytes; `None` stops offering it.
+    ///
+    /// The unit hashes are taken from the stored content. The content's
+    /// price must cover a tinybar per unit.
+    pub fn set_content_metering(&self, hash: &Hash, unit_size: Option<u64>) -> OpsResult<()> {
+        // Load manifest
+        let mut manifest = self
+            .state
+            .manifests
+            .load(hash)?
+            .ok_or(OpsError::ManifestNotFound(*hash))?;
+
+        // Verify ownership
+        if manifest.owner != self.peer_id() {
+            return Err(OpsError::AccessDenied);
+        }
+
+        match unit_size {
+            Some(unit_size) => {
+                // A collection is sold as a bundle of its items
+                if manifest.content_type == ContentType::Collection {
+                    return Err(OpsError::invalid_operation("collections can't be metered"));
+                }
+                let content = self
+                    .state
+                    .content
+                    .load(hash)?
+                    .ok_or(OpsError::NotFound(*hash))?;
+                manifest.metadata.metering = Some(ChunkTree::build(&content, unit_size));
+                validate_content(&content, &manifest)?;
+            }
+            None => {
+                manifest.metadata.metering = None;
+                validate_metadata(&manifest)?;
+            }
+        }
+
+        manifest.updated_at = current_timestamp();
+        self.state.manifests.update(&manifest)?;
+
+        Ok(())
+    }
+
+    /// Read metered content unit by unit, paying for each as it arrives.
+    ///
+    /// After each unit, `enough` is given the content read so far and stops
+    /// the read by returning `true`. The read also stops before a unit that
+    /// would take the total past `max_payment`; it fails with
+    /// `PaymentInsufficient` only if not even the first unit is affordable.
+    /// Own content is read locally and whole.
+    pub async fn query_metered<F>(
+        &self,
+        hash: &Hash,
+        max_payment: Amount,
+        mut enough: F,
+    ) -> OpsResult<MeteredResponse>
+    where
+        F: FnMut(&[u8]) -> bool,
+    {
+        let timestamp = current_timestamp();
+
+        // 1. Own content needs no payment
+        if let Some(manifest) = self.state.manifests.load(hash)? {
+            if manifest.owner == self.peer_id() {
+                let content = self
+                    .state
+                    .content
+                    .load(hash)?
+                    .ok_or(OpsError::NotFound(*hash))?;
+                let total_units = manifest.metadata.metered_units();
+                return Ok(MeteredResponse {
+                    content,
+                    receipt: MeteredReceipt {
+                        units: total_units,
+                        total_units,
+                        amount: 0,
+                        full_price: manifest.economics.price,
+                        unit_receipts: Vec::new(),
+                    },
+                    manifest,
+                });
+            }
+        }
+
+        // 2. Announcements don't carry unit hashes, so ask the provider for
+        // its manifest
+        let (network, provider, manifest) = self.provider_manifest(hash).await?;
+        let units = manifest
+            .metadata
+            .metering
+            .clone()
+            .ok_or(OpsError::UnitNotFound {
+                hash: *hash,
+                index: 0,
+            })?;
+        let total_units = units.len() as u32;
+
+        // 3. Check the owner once, before paying for anything
+        let owner = manifest.owner;
+        self.ensure_trusted(&manifest)?;
+        self.check_revenue_claim(&manifest).await?;
+        self.check_publisher_bond(&manifest).await?;
+
+        // 4. Pay for and fetch one unit at a time
+        let mut content = Vec::new();
+        let mut unit_receipts = Vec::new();
+        let mut paid: Amount = 0;
+        for index in 0..total_units {
+            let price = manifest.unit_price(index).unwrap_or_default();
+            if paid + price > max_payment {
+                if index == 0 {
+                    return Err(OpsError::PaymentInsufficient);
+                }
+                break;
+            }
+
+            let channel = self
+                .state
+                .channels
+                .get(&owner)?
+                .ok_or(OpsError::ChannelRequired)?;
+            let (payment, payment_nonce) = self.sign_tracked_payment(
+                &owner,
+                &channel,
+                price,
+                *hash,
+                manifest.provenance.root_l0l1.clone(),
+            )?;
+            let request = QueryRequestPayload {
+                hash: *hash,
+                query: None,
+                payment: Some(payment.clone()),
+                version_spec: None,
+                payment_nonce,
+                capability: None,
+                recipient_key: self.recipient_key(),
+                challenge_response: None,
+                request_id: Some(request_id_or_new()),
+                section: None,
+                unit: Some(index),
+                referrer: current_referrer(),
+                entitlement: None,
+            };
+
+            let mut response = self
+                .send_query_answering_challenge(&network, provider, request)
+                .await?;
+            self.open_response(&mut response)?;
+            if response.unit != Some(index) || !units.verify_chunk(index, &response.content) {
+                warn!(hash = %hash, index, provider = %provider, "Metered unit does not match its hash");
+                return Err(OpsError::ContentHashMismatch);
+            }
+
+            // Update channel balance after successful payment
+            self.update_payment_channel(&owner, payment)?;
+            paid += price;
+            content.extend_from_slice(&response.content);
+            unit_receipts.push(response.payment_receipt);
+
+            if enough(&content) {
+                break;
+            }
+        }
+        self.screen_remote_content(&manifest)?;
+
+        // 5. A read that reached the end is the whole content
+        let receipt = MeteredReceipt {
+            units: unit_receipts.len() as u32,
+            total_units,
+            amount: paid,
+            full_price: manifest.economics.price,
+            unit_receipts,
+        };
+        if receipt.is_complete() {
+            if !verify_content_hash(&content, hash) {
+                return Err(OpsError::ContentHashMismatch);
+            }
+            let proof = receipt
+                .unit_receipts
+                .last()
+                .cloned()
+                .unwrap_or(PaymentReceipt {
+                    payment_id: *hash,
+                    amount: 0,
+                    timestamp,
+                    channel_nonce: 0,
+                    distributor_signature: Signature::from_bytes([0u8; 64]),
+                    app_fee: 0,
+                    app_fee_recipient: None,
+                    referral_fee: 0,
+                    referrer: None,
+                });
+            self.state.cache.cache(CachedContent::new(
+                *hash,
+                content.clone(),
+                owner,
+                timestamp,
+                proof,
+            ))?;
+            self.store_remote_manifest(&manifest)?;
+        }
+
+        Ok(MeteredResponse {
+            content,
+            manifest,
+            receipt,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::NodeStateConfig;
+    use nodalync_types::{Metadata, Visibility};
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let config = NodeStateConfig::new(temp_dir.path());
+        let state = nodalync_store::NodeState::open(config).unwrap();
+
+        let (_, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+
+        let ops = DefaultNodeOperations::with_defaults(state, peer_id);
+        (ops, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_set_content_metering() {
+        let (ops, _temp) = create_test_ops();
+        let content = vec![b'x'; 5_000];
+        let hash = ops
+            .create_content(&content, Metadata::new("Log", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 1_000)
+            .await
+            .unwrap();
+
+        ops.set_content_metering(&hash, Some(2_048)).unwrap();
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        assert_eq!(manifest.metadata.metered_units(), 3);
+        assert_eq!(manifest.unit_price(2), Some(334));
+
+        // Every unit must still cost something
+        assert!(ops.set_content_price(&hash, 2).is_err());
+        // Units too small, or content in a single unit
+        assert!(ops.set_content_metering(&hash, Some(100)).is_err());
+        assert!(ops.set_content_metering(&hash, Some(8_192)).is_err());
+
+        // Own content is read whole, without payment
+        let response = ops.query_metered(&hash, 0, |_| true).await.unwrap();
+        assert_eq!(response.content, content);
+        assert!(response.receipt.is_complete());
+        assert_eq!(response.receipt.amount, 0);
+
+        ops.set_content_metering(&hash, None).unwrap();
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        assert!(manifest.metadata.metering.is_none());
+        ops.set_content_price(&hash, 2).unwrap();
+    }
+
+    #[test]
+    fn test_metered_receipt() {
+        let receipt = MeteredReceipt {
+            units: 2,
+            total_units: 5,
+            amount: 400,
+            full_price: 1_000,
+            unit_receipts: Vec::new(),
+        };
+        assert!(!receipt.is_complete());
+        assert_eq!(receipt.saved(), 600);
+    }
+}
@@ -0,0 +1,428 @@
+//! Content reports and moderation.
+//!
+//! Any peer can sign a [`ContentReport`] and broadcast it on the
+//! announcement topic. Receiving nodes apply their own
+//! [`ModerationConfig`](crate::config::ModerationConfig): accepted reports
+//! about content the node knows of queue it for review, and with an
+//! auto-hide threshold, enough reports from reputable peers hide it
+//! without waiting for the operator. The operator then hides or allows
+//! queued content.
+//!
+//! Hidden content is left out of search results. It can still be
+//! previewed or queried by hash, and our own content is never hidden:
+//! reports about it are queued for us to read, but withdrawing it is up to
+//! us (see [`tombstone`](crate::tombstone)).
+
+use nodalync_crypto::Hash;
+use nodalync_store::{
+    CacheStore, ManifestStore, ModerationEntry, ModerationStatus, ModerationStore, PeerStore,
+};
+use nodalync_types::{ContentReport, ReportReason};
+use nodalync_valid::{sign_report, validate_report, Validator};
+use nodalync_wire::ReportPayload;
+use tracing::{debug, info, warn};
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Report content to the network.
+    ///
+    /// Signs the report, records it in our own moderation queue, and
+    /// broadcasts it. The broadcast is best-effort. Our own content can't
+    /// be reported.
+    pub async fn report_content(
+        &self,
+        hash: &Hash,
+        reason: ReportReason,
+        comment: &str,
+    ) -> OpsResult<ContentReport> {
+        if self.is_own_content(hash)? {
+            return Err(OpsError::invalid_operation(
+                "cannot report your own content",
+            ));
+        }
+
+        let private_key = self.private_key().ok_or(OpsError::PrivateKeyRequired)?;
+        let mut report = ContentReport::new(
+            *hash,
+            self.peer_id(),
+            private_key.public_key(),
+            reason,
+            comment,
+            current_timestamp(),
+        );
+        sign_report(private_key, &mut report);
+        validate_report(&report)?;
+
+        if !self
+            .state
+            .moderation
+            .store_report(&report, report.created_at)?
+        {
+            debug!(hash = %hash, "Content already reported by us");
+        }
+
+        if let Some(network) = self.network() {
+            let payload = ReportPayload {
+                report: report.clone(),
+            };
+            if let Err(e) = network.broadcast_report(payload).await {
+                warn!(hash = %hash, "Report broadcast failed (kept locally): {}", e);
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Handle a content report broadcast by another peer.
+    ///
+    /// Ignored when reports aren't accepted, or when we know nothing about
+    /// the content. Otherwise the report is validated and stored, queuing
+    /// the content for review, and the auto-hide threshold is checked.
+    /// Returns whether a new report was recorded.
+    pub fn handle_report(&self, payload: &ReportPayload) -> OpsResult<bool> {
+        let report = &payload.report;
+        let hash = report.content_hash;
+
+        if !self.config.moderation.accept_reports || report.reporter == self.peer_id() {
+            return Ok(false);
+        }
+        validate_report(report)?;
+
+        let own_content = self.is_own_content(&hash)?;
+        let known = own_content
+            || self.state.get_announcement(&hash).is_some()
+            || self.state.cache.is_cached(&hash);
+        if !known {
+            debug!(hash = %hash, "Ignoring report for unknown content");
+            return Ok(false);
+        }
+
+        if !self
+            .state
+            .moderation
+            .store_report(report, current_timestamp())?
+        {
+            return Ok(false);
+        }
+        info!(hash = %hash, reporter = %report.reporter, reason = %report.reason, "Content reported");
+
+        if !own_content {
+            self.apply_auto_hide(&hash)?;
+        }
+        Ok(true)
+    }
+
+    /// Hide queued content once enough reputable peers have reported it.
+    fn apply_auto_hide(&self, hash: &Hash) -> OpsResult<()> {
+        let Some(threshold) = self.config.moderation.auto_hide_threshold else {
+            return Ok(());
+        };
+        // Operator decisions stand
+        if self.state.moderation.get_status(hash)? != Some(ModerationStatus::Pending) {
+            return Ok(());
+        }
+
+        let min_reputation = self.config.moderation.min_reporter_reputation;
+        let reputable = self
+            .state
+            .moderation
+            .reports_for(hash)?
+            .iter()
+            .filter(|report| {
+                let reputation = self
+                    .state
+                    .peers
+                    .get(&report.reporter)
+                    .ok()
+                    .flatten()
+                    .map(|info| info.reputation)
+                    .unwrap_or(0);
+                reputation >= min_reputation
+            })
+            .count();
+
+        if reputable >= threshold as usize {
+            self.state.moderation.set_status(
+                hash,
+                ModerationStatus::AutoHidden,
+                current_timestamp(),
+            )?;
+            info!(hash = %hash, reports = reputable, "Content hidden after reports from reputable peers");
+        }
+        Ok(())
+    }
+
+    /// List content awaiting review: reported content with no decision
+    /// yet, and content hidden automatically.
+    pub fn moderation_queue(&self) -> OpsResult<Vec<ModerationEntry>> {
+        Ok(self
+            .state
+            .moderation
+            .list(None)?
+            .into_iter()
+            .filter(|entry| entry.status.needs_review())
+            .collect())
+    }
+
+    /// List moderated content, optionally filtered by status.
+    pub fn list_moderation(
+        &self,
+        status: Option<ModerationStatus>,
+    ) -> OpsResult<Vec<ModerationEntry>> {
+        Ok(self.state.moderation.list(status)?)
+    }
+
+    /// Get the reports received for a content hash, oldest first.
+    pub fn get_reports(&self, hash: &Hash) -> OpsResult<Vec<ContentReport>> {
+        Ok(self.state.moderation.reports_for(hash)?)
+    }
+
+    /// Get the moderation status of a content hash.
+    pub fn get_moderation_status(&self, hash: &Hash) -> OpsResult<Option<ModerationStatus>> {
+        Ok(self.state.moderation.get_status(hash)?)
+    }
+
+    /// Hide content from search results.
+    ///
+    /// Works whether or not the content was reported. Our own content
+    /// can't be hidden; unpublish it instead.
+    pub fn hide_content(&self, hash: &Hash) -> OpsResult<()> {
+        if self.is_own_content(hash)? {
+            return Err(OpsError::invalid_operation(
+                "cannot hide your own content; unpublish it instead",
+            ));
+        }
+        self.state
+            .moderation
+            .set_status(hash, ModerationStatus::Hidden, current_timestamp())?;
+        Ok(())
+    }
+
+    /// Keep reported content visible, dismissing its reports.
+    ///
+    /// Also un-hides content hidden manually or automatically. Later
+    /// reports don't hide it again.
+    pub fn allow_content(&self, hash: &Hash) -> OpsResult<()> {
+        self.state
+            .moderation
+            .set_status(hash, ModerationStatus::Allowed, current_timestamp())?;
+        Ok(())
+    }
+
+    /// Check whether content is hidden on this node.
+    pub(crate) fn is_hidden(&self, hash: &Hash) -> bool {
+        match self.state.moderation.get_status(hash) {
+            Ok(status) => status.is_some_and(|s| s.is_hidden()),
+            Err(e) => {
+                warn!(hash = %hash, error = %e, "Failed to look up moderation status");
+                false
+            }
+        }
+    }
+
+    /// Check whether we own the content.
+    fn is_own_content(&self, hash: &Hash) -> OpsResult<bool> {
+        Ok(self
+            .state
+            .manifests
+            .load(hash)?
+            .is_some_and(|m| m.owner == self.peer_id()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ModerationConfig, OpsConfig};
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig, PeerInfo};
+    use nodalync_types::{ContentType, L1Summary, Metadata};
+    use nodalync_wire::AnnouncePayload;
+    use tempfile::TempDir;
+
+    fn create_test_ops(moderation: ModerationConfig) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let config = OpsConfig::default().with_moderation(moderation);
+        let mut ops =
+            DefaultNodeOperations::with_config(state, peer_id_from_public_key(&public_key), config);
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    /// Store an announcement so the node knows of the content.
+    fn announce(ops: &mut DefaultNodeOperations, content: &[u8], title: &str) -> Hash {
+        let hash = content_hash(content);
+        ops.state.store_announcement(AnnouncePayload {
+            hash,
+            content_type: ContentType::L0,
+            title: title.to_string(),
+            l1_summary: L1Summary::empty(hash),
+            price: 0,
+            addresses: vec![],
+            publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
+        });
+        hash
+    }
+
+    /// A reporter with the given reputation on `moderator`.
+    fn reporter(
+        moderator: &mut DefaultNodeOperations,
+        reputation: i64,
+    ) -> (DefaultNodeOperations, TempDir) {
+        let (reporter, dir) = create_test_ops(ModerationConfig::default());
+        let public_key = reporter.private_key().unwrap().public_key();
+        moderator
+            .state
+            .peers
+            .upsert(
+                &PeerInfo::new(reporter.peer_id(), public_key, vec![], 0)
+                    .with_reputation(reputation),
+            )
+            .unwrap();
+        (reporter, dir)
+    }
+
+    async fn report(reporter: &mut DefaultNodeOperations, hash: &Hash) -> ReportPayload {
+        let report = reporter
+            .report_content(hash, ReportReason::Spam, "Link farm")
+            .await
+            .unwrap();
+        ReportPayload { report }
+    }
+
+    #[tokio::test]
+    async fn test_report_queues_content_for_review() {
+        let (mut moderator, _dir) = create_test_ops(ModerationConfig::default());
+        let hash = announce(&mut moderator, b"spam", "Cheap pills");
+        let (mut reporter, _reporter_dir) = reporter(&mut moderator, 5);
+
+        let payload = report(&mut reporter, &hash).await;
+        assert_eq!(payload.report.reporter, reporter.peer_id());
+        // The reporter keeps its own report
+        assert_eq!(reporter.get_reports(&hash).unwrap().len(), 1);
+
+        assert!(moderator.handle_report(&payload).unwrap());
+        assert!(!moderator.handle_report(&payload).unwrap());
+        assert_eq!(
+            moderator.get_moderation_status(&hash).unwrap(),
+            Some(ModerationStatus::Pending)
+        );
+        let queue = moderator.moderation_queue().unwrap();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].report_count, 1);
+
+        // Manual decisions
+        moderator.hide_content(&hash).unwrap();
+        assert!(moderator.is_hidden(&hash));
+        assert!(moderator
+            .search_network("pills", None, 10)
+            .await
+            .unwrap()
+            .is_empty());
+        moderator.allow_content(&hash).unwrap();
+        assert!(!moderator.is_hidden(&hash));
+        assert!(moderator.moderation_queue().unwrap().is_empty());
+        assert_eq!(
+            moderator
+                .search_network("pills", None, 10)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Tampered reports are rejected
+        let mut forged = report(&mut reporter, &hash).await;
+        forged.report.reason = ReportReason::Illegal;
+        assert!(moderator.handle_report(&forged).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_auto_hide_after_reputable_reports() {
+        let (mut moderator, _dir) = create_test_ops(
+            ModerationConfig::default()
+                .with_auto_hide_threshold(2)
+                .with_min_reporter_reputation(5),
+        );
+        let hash = announce(&mut moderator, b"spam", "Cheap pills");
+        let (mut trusted, _trusted_dir) = reporter(&mut moderator, 10);
+        let (mut unknown, _unknown_dir) = reporter(&mut moderator, 0);
+        let (mut also_trusted, _also_trusted_dir) = reporter(&mut moderator, 5);
+
+        for reporter in [&mut trusted, &mut unknown] {
+            let payload = report(reporter, &hash).await;
+            assert!(moderator.handle_report(&payload).unwrap());
+        }
+        // Only one of the two reporters is reputable enough
+        assert!(!moderator.is_hidden(&hash));
+
+        let payload = report(&mut also_trusted, &hash).await;
+        assert!(moderator.handle_report(&payload).unwrap());
+        assert_eq!(
+            moderator.get_moderation_status(&hash).unwrap(),
+            Some(ModerationStatus::AutoHidden)
+        );
+        assert!(moderator
+            .search_network("pills", None, 10)
+            .await
+            .unwrap()
+            .is_empty());
+        // Auto-hidden content stays queued for the operator
+        assert_eq!(moderator.moderation_queue().unwrap().len(), 1);
+
+        // Once allowed, further reports don't hide it again
+        moderator.allow_content(&hash).unwrap();
+        let (mut another, _another_dir) = reporter(&mut moderator, 50);
+        let payload = report(&mut another, &hash).await;
+        assert!(moderator.handle_report(&payload).unwrap());
+        assert!(!moderator.is_hidden(&hash));
+    }
+
+    #[tokio::test]
+    async fn test_reports_ignored_by_policy() {
+        let (mut moderator, _dir) =
+            create_test_ops(ModerationConfig::default().with_auto_hide_threshold(1));
+        let (mut reporter, _reporter_dir) = reporter(&mut moderator, 10);
+
+        // Unknown content
+        let unknown = content_hash(b"never seen");
+        let payload = report(&mut reporter, &unknown).await;
+        assert!(!moderator.handle_report(&payload).unwrap());
+        assert!(moderator.get_moderation_status(&unknown).unwrap().is_none());
+
+        // Our own content is queued but never hidden
+        let content = b"My notes";
+        let own = moderator
+            .create_content(content, Metadata::new("Notes", content.len() as u64))
+            .unwrap();
+        let payload = report(&mut reporter, &own).await;
+        assert!(moderator.handle_report(&payload).unwrap());
+        assert!(!moderator.is_hidden(&own));
+        assert!(moderator.hide_content(&own).is_err());
+        assert!(moderator
+            .report_content(&own, ReportReason::Other, "")
+            .await
+            .is_err());
+
+        // Reports not accepted at all
+        let (mut closed, _closed_dir) =
+            create_test_ops(ModerationConfig::default().with_accept_reports(false));
+        let hash = announce(&mut closed, b"spam", "Cheap pills");
+        let payload = report(&mut reporter, &hash).await;
+        assert!(!closed.handle_report(&payload).unwrap());
+        assert!(closed.get_reports(&hash).unwrap().is_empty());
+    }
+}
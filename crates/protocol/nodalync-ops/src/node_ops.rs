@@ -3,16 +3,40 @@
 //! This module provides the `NodeOperations` struct that implements
 //! the `Operations` trait, orchestrating all protocol functionality.
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
-use nodalync_crypto::{PeerId, PrivateKey, Timestamp};
+use nodalync_crypto::{PeerId, PrivateKey};
 use nodalync_net::Network;
 use nodalync_settle::Settlement;
 use nodalync_store::NodeState;
 use nodalync_valid::Validator;
 
+use crate::announce_filter::AnnouncementFilterState;
+use crate::attestation::AttestationCache;
+use crate::bond::BondCache;
+use crate::challenge::ChallengeTracker;
+use crate::channel::ChannelLocks;
+use crate::clock::ClockSkew;
 use crate::config::OpsConfig;
+use crate::events::{OpsEvent, EVENT_BUS_CAPACITY};
 use crate::extraction::L1Extractor;
+use crate::ingest::AnnouncementIngest;
+use crate::notification::Notifier;
+use crate::retry::QuerySlots;
+use crate::usage::UsageReportLimiter;
+use crate::webhook::WebhookTransport;
+
+/// Window over which `AutoOpenPolicy::max_opens_per_day` is counted.
+const AUTO_OPEN_WINDOW: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+/// Lock state shared between concurrent operations.
+///
+/// The guarded state is caches, counters and rate-limit windows that are
+/// consistent after every update, so a panic while holding the lock leaves
+/// nothing to repair and the poison is ignored.
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
 
 /// Main operations implementation.
 ///
@@ -56,11 +80,44 @@ where
     ///
     /// Required for paid queries - without this, only free content can be queried.
     private_key: Option<PrivateKey>,
+    /// Optional transport posting webhook notifications.
+    ///
+    /// When `None`, notifications are queued but not delivered.
+    webhook_transport: Option<Arc<dyn WebhookTransport>>,
+    /// Notifiers activity digests are sent through.
+    notifiers: Vec<Arc<dyn Notifier>>,
     /// Timestamp of the last auto-deposit for rate limiting.
     ///
     /// Used to prevent rapid deposits from malicious channel open spam.
     /// This is a global cooldown (not per-peer) for simplicity.
-    last_auto_deposit: Option<std::time::Instant>,
+    last_auto_deposit: Mutex<Option<std::time::Instant>>,
+    /// When each channel auto-open in the last day happened.
+    ///
+    /// Enforces `AutoOpenPolicy::max_opens_per_day`. Like the auto-deposit
+    /// cooldown, this does not persist across restarts.
+    auto_opens: Mutex<Vec<std::time::Instant>>,
+    /// Rate-limit windows and counters for the incoming announcement filter.
+    pub(crate) announcement_filter: Mutex<AnnouncementFilterState>,
+    /// Batched announcement ingestion, once started.
+    pub(crate) announcement_ingest: Option<AnnouncementIngest>,
+    /// Per-peer rate limiting of incoming usage reports.
+    pub(crate) usage_report_limiter: Mutex<UsageReportLimiter>,
+    /// Cached results of checking attestation claims on-chain.
+    pub(crate) attestation_cache: Mutex<AttestationCache>,
+    /// Cached on-chain bonds of other publishers.
+    pub(crate) bond_cache: Mutex<BondCache>,
+    /// Query challenges issued and not yet answered.
+    pub(crate) query_challenges: Mutex<ChallengeTracker>,
+    /// Estimated offset of the local clock from the network's.
+    pub(crate) clock_skew: Mutex<ClockSkew>,
+    /// Per-peer locks over channel read-modify-write sequences.
+    pub(crate) channel_locks: ChannelLocks,
+    /// Sender side of the operations event bus.
+    pub(crate) events: tokio::sync::broadcast::Sender<OpsEvent>,
+    /// When these operations were created, for the advertised uptime.
+    pub(crate) started_at: std::time::Instant,
+    /// Queries being served, against the concurrency limit.
+    pub(crate) query_slots: QuerySlots,
 }
 
 impl<V, E> NodeOperations<V, E>
@@ -85,7 +142,21 @@ where
             network: None,
             settlement: None,
             private_key: None,
-            last_auto_deposit: None,
+            webhook_transport: None,
+            notifiers: Vec::new(),
+            last_auto_deposit: Mutex::new(None),
+            auto_opens: Mutex::new(Vec::new()),
+            announcement_filter: Default::default(),
+            announcement_ingest: None,
+            usage_report_limiter: Default::default(),
+            attestation_cache: Default::default(),
+            bond_cache: Default::default(),
+            query_challenges: Default::default(),
+            clock_skew: Default::default(),
+            channel_locks: Default::default(),
+            events: tokio::sync::broadcast::channel(EVENT_BUS_CAPACITY).0,
+            started_at: std::time::Instant::now(),
+            query_slots: QuerySlots::default(),
         }
     }
 
@@ -107,7 +178,21 @@ where
             network: Some(network),
             settlement: None,
             private_key: None,
-            last_auto_deposit: None,
+            webhook_transport: None,
+            notifiers: Vec::new(),
+            last_auto_deposit: Mutex::new(None),
+            auto_opens: Mutex::new(Vec::new()),
+            announcement_filter: Default::default(),
+            announcement_ingest: None,
+            usage_report_limiter: Default::default(),
+            attestation_cache: Default::default(),
+            bond_cache: Default::default(),
+            query_challenges: Default::default(),
+            clock_skew: Default::default(),
+            channel_locks: Default::default(),
+            events: tokio::sync::broadcast::channel(EVENT_BUS_CAPACITY).0,
+            started_at: std::time::Instant::now(),
+            query_slots: QuerySlots::default(),
         }
     }
 
@@ -129,7 +214,21 @@ where
             network: None,
             settlement: Some(settlement),
             private_key: None,
-            last_auto_deposit: None,
+            webhook_transport: None,
+            notifiers: Vec::new(),
+            last_auto_deposit: Mutex::new(None),
+            auto_opens: Mutex::new(Vec::new()),
+            announcement_filter: Default::default(),
+            announcement_ingest: None,
+            usage_report_limiter: Default::default(),
+            attestation_cache: Default::default(),
+            bond_cache: Default::default(),
+            query_challenges: Default::default(),
+            clock_skew: Default::default(),
+            channel_locks: Default::default(),
+            events: tokio::sync::broadcast::channel(EVENT_BUS_CAPACITY).0,
+            started_at: std::time::Instant::now(),
+            query_slots: QuerySlots::default(),
         }
     }
 
@@ -152,7 +251,21 @@ where
             network: Some(network),
             settlement: Some(settlement),
             private_key: None,
-            last_auto_deposit: None,
+            webhook_transport: None,
+            notifiers: Vec::new(),
+            last_auto_deposit: Mutex::new(None),
+            auto_opens: Mutex::new(Vec::new()),
+            announcement_filter: Default::default(),
+            announcement_ingest: None,
+            usage_report_limiter: Default::default(),
+            attestation_cache: Default::default(),
+            bond_cache: Default::default(),
+            query_challenges: Default::default(),
+            clock_skew: Default::default(),
+            channel_locks: Default::default(),
+            events: tokio::sync::broadcast::channel(EVENT_BUS_CAPACITY).0,
+            started_at: std::time::Instant::now(),
+            query_slots: QuerySlots::default(),
         }
     }
 
@@ -216,6 +329,26 @@ where
         self.settlement = None;
     }
 
+    /// Get a reference to the webhook transport (if available).
+    pub fn webhook_transport(&self) -> Option<&Arc<dyn WebhookTransport>> {
+        self.webhook_transport.as_ref()
+    }
+
+    /// Set the transport posting webhook notifications.
+    pub fn set_webhook_transport(&mut self, transport: Arc<dyn WebhookTransport>) {
+        self.webhook_transport = Some(transport);
+    }
+
+    /// Get the notifiers activity digests are sent through.
+    pub fn notifiers(&self) -> &[Arc<dyn Notifier>] {
+        &self.notifiers
+    }
+
+    /// Add a notifier activity digests are sent through.
+    pub fn add_notifier(&mut self, notifier: Arc<dyn Notifier>) {
+        self.notifiers.push(notifier);
+    }
+
     /// Get a reference to the private key (if available).
     pub fn private_key(&self) -> Option<&PrivateKey> {
         self.private_key.as_ref()
@@ -239,20 +372,20 @@ where
     /// Mark that an auto-deposit was just performed.
     ///
     /// This sets the cooldown timestamp to prevent rapid deposits.
-    pub fn mark_auto_deposit(&mut self) {
-        self.last_auto_deposit = Some(std::time::Instant::now());
+    pub fn mark_auto_deposit(&self) {
+        *lock(&self.last_auto_deposit) = Some(std::time::Instant::now());
     }
 
     /// Get the timestamp of the last auto-deposit.
     pub fn last_auto_deposit(&self) -> Option<std::time::Instant> {
-        self.last_auto_deposit
+        *lock(&self.last_auto_deposit)
     }
 
     /// Check if an auto-deposit is allowed based on the cooldown.
     ///
     /// Returns true if no deposit has been made, or if the cooldown has elapsed.
     pub fn can_auto_deposit(&self) -> bool {
-        match self.last_auto_deposit {
+        match self.last_auto_deposit() {
             None => true,
             Some(last) => {
                 let cooldown =
@@ -261,6 +394,22 @@ where
             }
         }
     }
+
+    /// Record that a channel was just auto-opened.
+    pub fn mark_auto_open(&self) {
+        let now = std::time::Instant::now();
+        let mut auto_opens = lock(&self.auto_opens);
+        auto_opens.retain(|t| now.duration_since(*t) < AUTO_OPEN_WINDOW);
+        auto_opens.push(now);
+    }
+
+    /// Number of channels auto-opened in the last 24 hours.
+    pub fn auto_opens_today(&self) -> u32 {
+        lock(&self.auto_opens)
+            .iter()
+            .filter(|t| t.elapsed() < AUTO_OPEN_WINDOW)
+            .count() as u32
+    }
 }
 
 /// Default NodeOperations with DefaultValidator (using PeerStoreKeyLookup) and RuleBasedExtractor.
@@ -422,13 +571,7 @@ impl DefaultNodeOperations {
     }
 }
 
-/// Get current timestamp in milliseconds since Unix epoch.
-pub fn current_timestamp() -> Timestamp {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .expect("Time went backwards")
-        .as_millis() as Timestamp
-}
+pub use nodalync_crypto::current_timestamp;
 
 #[cfg(test)]
 mod tests {
@@ -455,6 +598,12 @@ mod tests {
         assert_eq!(ops.config().max_preview_mentions, 5);
     }
 
+    #[test]
+    fn test_node_ops_shareable_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<DefaultNodeOperations>();
+    }
+
     #[test]
     fn test_peer_id() {
         let (ops, _temp) = create_test_node_ops();
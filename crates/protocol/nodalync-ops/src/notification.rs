@@ -0,0 +1,442 @@
+//! Digests of earnings and activity.
+//!
+//! An [`ActivityDigest`] summarizes one day or week: earnings and queries
+//! from the access log, the content that earned the most, consumers seen
+//! for the first time, and settlement batches that failed on-chain. With
+//! `NotificationConfig::daily` or `weekly` set, `send_due_digests` builds
+//! a digest once each period ends and hands it to every registered
+//! [`Notifier`] (the CLI provides email and webhook ones; a desktop app can
+//! register its own).
+//!
+//! Only the most recent period is sent after downtime, and the first run
+//! starts counting from the current period rather than reporting history.
+
+use std::collections::{HashMap, HashSet};
+
+use async_trait::async_trait;
+use nodalync_crypto::{Hash, PeerId, Timestamp};
+use nodalync_store::{
+    AccessKind, AccessLogStore, DigestPeriod, DigestStore, ManifestStore, SettlementFailure,
+};
+use nodalync_types::Amount;
+use nodalync_valid::Validator;
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::error::OpsResult;
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+/// Length of a day in milliseconds.
+const DAY_MS: Timestamp = 86_400_000;
+
+/// Offset of the first Monday after the epoch (a Thursday).
+const MONDAY_OFFSET_MS: Timestamp = 4 * DAY_MS;
+
+/// Length of a digest period in milliseconds.
+pub fn period_length_ms(period: DigestPeriod) -> Timestamp {
+    match period {
+        DigestPeriod::Daily => DAY_MS,
+        DigestPeriod::Weekly => 7 * DAY_MS,
+    }
+}
+
+/// Start and end of the last period that ended at or before `now`.
+pub fn last_complete_period(period: DigestPeriod, now: Timestamp) -> (Timestamp, Timestamp) {
+    let length = period_length_ms(period);
+    let offset = match period {
+        DigestPeriod::Daily => 0,
+        DigestPeriod::Weekly => MONDAY_OFFSET_MS,
+    };
+    let end = now.saturating_sub(offset) / length * length + offset;
+    let end = if end > now { end - length } else { end };
+    (end.saturating_sub(length), end)
+}
+
+/// Earnings of one piece of content within a digest period.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TopContent {
+    /// Content hash.
+    pub hash: Hash,
+    /// Title from the manifest, empty if the manifest is gone.
+    pub title: String,
+    /// Queries served, paid or free.
+    pub queries: u64,
+    /// Revenue earned.
+    pub revenue: Amount,
+}
+
+/// Summary of earnings and activity over one period.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ActivityDigest {
+    /// Period summarized.
+    pub period: DigestPeriod,
+    /// Start of the period (ms, inclusive).
+    pub start: Timestamp,
+    /// End of the period (ms, exclusive).
+    pub end: Timestamp,
+    /// Node the digest is for.
+    pub node: PeerId,
+    /// Revenue from queries served.
+    pub earnings: Amount,
+    /// Queries served with a payment.
+    pub paid_queries: u64,
+    /// Queries of free content.
+    pub free_queries: u64,
+    /// Previews served.
+    pub previews: u64,
+    /// Distinct peers that previewed or queried content.
+    pub consumers: u64,
+    /// Of those, peers never seen before the period.
+    pub new_consumers: u64,
+    /// Content that earned the most, highest first.
+    pub top_content: Vec<TopContent>,
+    /// Settlement batches that failed on-chain, oldest first.
+    pub failed_settlements: Vec<SettlementFailure>,
+}
+
+impl ActivityDigest {
+    /// Whether nothing happened in the period.
+    pub fn is_empty(&self) -> bool {
+        self.paid_queries == 0
+            && self.free_queries == 0
+            && self.previews == 0
+            && self.failed_settlements.is_empty()
+    }
+}
+
+/// Delivers activity digests (email, webhook, desktop notification).
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Short name used in logs (e.g. `email`).
+    fn name(&self) -> &str;
+
+    /// Deliver a digest.
+    async fn notify(&self, digest: &ActivityDigest) -> Result<(), String>;
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Build the digest for `[start, end)`.
+    pub fn activity_digest(
+        &self,
+        period: DigestPeriod,
+        start: Timestamp,
+        end: Timestamp,
+    ) -> OpsResult<ActivityDigest> {
+        let records = self.state.access_log.between(start, end)?;
+
+        let mut digest = ActivityDigest {
+            period,
+            start,
+            end,
+            node: self.peer_id(),
+            earnings: 0,
+            paid_queries: 0,
+            free_queries: 0,
+            previews: 0,
+            consumers: 0,
+            new_consumers: self.state.access_log.first_seen_between(start, end)?.len() as u64,
+            top_content: Vec::new(),
+            failed_settlements: self.state.digests.settlement_failures(start, end)?,
+        };
+
+        let mut consumers = HashSet::new();
+        let mut content: HashMap<Hash, (u64, Amount)> = HashMap::new();
+        for record in &records {
+            consumers.insert(record.requester);
+            match record.kind {
+                AccessKind::Preview => digest.previews += 1,
+                AccessKind::Query => {
+                    if record.amount > 0 {
+                        digest.paid_queries += 1;
+                    } else {
+                        digest.free_queries += 1;
+                    }
+                    digest.earnings = digest.earnings.saturating_add(record.amount);
+                    let entry = content.entry(record.content_hash).or_default();
+                    entry.0 += 1;
+                    entry.1 = entry.1.saturating_add(record.amount);
+                }
+            }
+        }
+        digest.consumers = consumers.len() as u64;
+
+        let mut ranked: Vec<(Hash, u64, Amount)> = content
+            .into_iter()
+            .map(|(hash, (queries, revenue))| (hash, queries, revenue))
+            .collect();
+        ranked.sort_by(|a, b| b.2.cmp(&a.2).then(b.1.cmp(&a.1)).then(a.0 .0.cmp(&b.0 .0)));
+        ranked.truncate(self.config.notifications.top_content);
+        for (hash, queries, revenue) in ranked {
+            let title = self
+                .state
+                .manifests
+                .load(&hash)?
+                .map(|m| m.metadata.title)
+                .unwrap_or_default();
+            digest.top_content.push(TopContent {
+                hash,
+                title,
+                queries,
+                revenue,
+            });
+        }
+
+        Ok(digest)
+    }
+
+    /// Send digests for periods that ended since the last ones sent.
+    ///
+    /// A digest counts as sent once any notifier accepts it; when all of
+    /// them fail it is retried on the next call. Returns the digests sent,
+    /// or `None` without notifiers.
+    pub async fn send_due_digests(&self) -> OpsResult<Option<Vec<ActivityDigest>>> {
+        if self.notifiers().is_empty() {
+            return Ok(None);
+        }
+
+        let now = current_timestamp();
+        let mut sent = Vec::new();
+        for period in self.config.notifications.periods() {
+            let (start, end) = last_complete_period(period, now);
+            match self.state.digests.sent_through(period)? {
+                Some(through) if through >= end => continue,
+                Some(_) => {}
+                None => {
+                    // Start with the current period instead of reporting history
+                    self.state.digests.set_sent_through(period, end)?;
+                    continue;
+                }
+            }
+
+            let digest = self.activity_digest(period, start, end)?;
+            if self.notify_all(&digest).await {
+                self.state.digests.set_sent_through(period, end)?;
+                info!(period = %period, earnings = digest.earnings, "Activity digest sent");
+                sent.push(digest);
+            }
+        }
+
+        Ok(Some(sent))
+    }
+
+    /// Send a digest through every notifier.
+    ///
+    /// Returns whether any notifier accepted it.
+    pub async fn notify_all(&self, digest: &ActivityDigest) -> bool {
+        let mut delivered = false;
+        for notifier in self.notifiers() {
+            match notifier.notify(digest).await {
+                Ok(()) => delivered = true,
+                Err(e) => {
+                    warn!(notifier = notifier.name(), error = %e, "Failed to send activity digest")
+                }
+            }
+        }
+        delivered
+    }
+
+    /// Record a settlement batch that failed on-chain for the digests.
+    ///
+    /// Failing to record it must not hide the settlement error, so errors
+    /// are only logged.
+    pub(crate) fn record_settlement_failure(&self, batch_id: Hash, amount: Amount, error: &str) {
+        let failure = SettlementFailure {
+            batch_id,
+            amount,
+            error: error.to_string(),
+            timestamp: current_timestamp(),
+        };
+        if let Err(e) = self.state.digests.record_settlement_failure(&failure) {
+            warn!(batch_id = %batch_id, error = %e, "Failed to record settlement failure");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{NotificationConfig, OpsConfig};
+    use crate::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_store::{AccessRecord, AccessRequester, NodeState, NodeStateConfig};
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
+
+    /// Records digests, failing when told to.
+    #[derive(Default)]
+    struct RecordingNotifier {
+        digests: Mutex<Vec<ActivityDigest>>,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl Notifier for RecordingNotifier {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn notify(&self, digest: &ActivityDigest) -> Result<(), String> {
+            if self.fail {
+                return Err("unreachable".to_string());
+            }
+            self.digests.lock().unwrap().push(digest.clone());
+            Ok(())
+        }
+    }
+
+    fn create_ops(notifications: NotificationConfig) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (_, public_key) = generate_identity();
+        let ops = DefaultNodeOperations::with_config(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default().with_notifications(notifications),
+        );
+        (ops, temp_dir)
+    }
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    fn access(
+        ops: &DefaultNodeOperations,
+        content: &[u8],
+        peer: PeerId,
+        kind: AccessKind,
+        amount: Amount,
+        timestamp: Timestamp,
+    ) {
+        ops.state
+            .access_log
+            .record(&AccessRecord::new(
+                content_hash(content),
+                AccessRequester::Peer(peer),
+                kind,
+                amount,
+                timestamp,
+            ))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_last_complete_period() {
+        // 2024-01-17 (a Wednesday) 15:00 UTC
+        let now = 1_705_503_600_000;
+        let (start, end) = last_complete_period(DigestPeriod::Daily, now);
+        assert_eq!(end, 1_705_449_600_000); // 2024-01-17 00:00
+        assert_eq!(start, end - DAY_MS);
+
+        let (start, end) = last_complete_period(DigestPeriod::Weekly, now);
+        assert_eq!(end, 1_705_276_800_000); // Monday 2024-01-15 00:00
+        assert_eq!(start, end - 7 * DAY_MS);
+
+        // A boundary counts as the end of the period before it
+        assert_eq!(last_complete_period(DigestPeriod::Daily, end).1, end);
+    }
+
+    #[test]
+    fn test_activity_digest() {
+        let (ops, _temp) = create_ops(NotificationConfig::default().with_top_content(2));
+        let regular = test_peer_id();
+        let newcomer = test_peer_id();
+        let day = DAY_MS;
+
+        access(&ops, b"a", regular, AccessKind::Query, 10, 100);
+        access(&ops, b"a", regular, AccessKind::Query, 10, day + 100);
+        access(&ops, b"b", newcomer, AccessKind::Query, 50, day + 200);
+        access(&ops, b"c", newcomer, AccessKind::Query, 0, day + 300);
+        access(&ops, b"c", newcomer, AccessKind::Preview, 0, day + 400);
+        access(&ops, b"a", newcomer, AccessKind::Query, 10, 2 * day + 100);
+        ops.record_settlement_failure(content_hash(b"batch"), 70, "timeout");
+
+        let digest = ops
+            .activity_digest(DigestPeriod::Daily, day, 2 * day)
+            .unwrap();
+        assert_eq!(digest.earnings, 60);
+        assert_eq!(digest.paid_queries, 2);
+        assert_eq!(digest.free_queries, 1);
+        assert_eq!(digest.previews, 1);
+        assert_eq!(digest.consumers, 2);
+        assert_eq!(digest.new_consumers, 1);
+        let top: Vec<(Hash, Amount)> = digest
+            .top_content
+            .iter()
+            .map(|c| (c.hash, c.revenue))
+            .collect();
+        assert_eq!(
+            top,
+            vec![(content_hash(b"b"), 50), (content_hash(b"a"), 10)]
+        );
+        // The failure was recorded now, outside the period
+        assert!(digest.failed_settlements.is_empty());
+
+        let now = current_timestamp();
+        let digest = ops
+            .activity_digest(DigestPeriod::Daily, now - day, now + 1)
+            .unwrap();
+        assert_eq!(digest.failed_settlements.len(), 1);
+        assert_eq!(digest.failed_settlements[0].error, "timeout");
+        assert!(!digest.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_send_due_digests() {
+        let (mut ops, _temp) =
+            create_ops(NotificationConfig::default().with_period(DigestPeriod::Daily, true));
+        assert_eq!(ops.send_due_digests().await.unwrap(), None);
+
+        let notifier = Arc::new(RecordingNotifier::default());
+        ops.add_notifier(notifier.clone());
+
+        // The first run only starts the clock
+        assert!(ops.send_due_digests().await.unwrap().unwrap().is_empty());
+        let (_, end) = last_complete_period(DigestPeriod::Daily, current_timestamp());
+        assert_eq!(
+            ops.state.digests.sent_through(DigestPeriod::Daily).unwrap(),
+            Some(end)
+        );
+
+        // A period ended since the last digest
+        ops.state
+            .digests
+            .set_sent_through(DigestPeriod::Daily, end - DAY_MS)
+            .unwrap();
+        let sent = ops.send_due_digests().await.unwrap().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!((sent[0].start, sent[0].end), (end - DAY_MS, end));
+        assert_eq!(notifier.digests.lock().unwrap().len(), 1);
+
+        // Nothing more is due
+        assert!(ops.send_due_digests().await.unwrap().unwrap().is_empty());
+        assert_eq!(notifier.digests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_digest_is_retried() {
+        let (mut ops, _temp) =
+            create_ops(NotificationConfig::default().with_period(DigestPeriod::Daily, true));
+        ops.add_notifier(Arc::new(RecordingNotifier {
+            fail: true,
+            ..Default::default()
+        }));
+        let (_, end) = last_complete_period(DigestPeriod::Daily, current_timestamp());
+        ops.state
+            .digests
+            .set_sent_through(DigestPeriod::Daily, end - DAY_MS)
+            .unwrap();
+
+        assert!(ops.send_due_digests().await.unwrap().unwrap().is_empty());
+        assert_eq!(
+            ops.state.digests.sent_through(DigestPeriod::Daily).unwrap(),
+            Some(end - DAY_MS)
+        );
+    }
+}
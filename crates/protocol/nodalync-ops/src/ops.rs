@@ -1,17 +1,35 @@
-//! Operations trait definition.
+//! Operations trait definitions.
 //!
-//! This module defines the main `Operations` trait that provides
-//! the protocol operation interface as specified in §7.
+//! The protocol operation interface specified in §7 is split by capability,
+//! so a node can implement just the part it needs (a read-only consumer
+//! implements [`QueryOps`], a serving-only replica [`ContentOps`] and
+//! [`PublishOps`]):
+//!
+//! - [`OpsContext`] - The local peer ID and clock, shared by all of them
+//! - [`ContentOps`] - Creating, updating and deriving content (§7.1), and
+//!   L2 entity graphs
+//! - [`PublishOps`] - Publishing and visibility (§7.1.3)
+//! - [`QueryOps`] - Previews, queries and versions (§7.2)
+//! - [`ChannelOps`] - Payment channels (§7.3)
+//! - [`SettlementOps`] - Settlement batches (§7.5)
+//!
+//! [`Operations`] combines them and is implemented for every type that
+//! implements all five.
 
 use async_trait::async_trait;
 use nodalync_crypto::{Hash, PeerId, Timestamp};
 use nodalync_types::{
-    AccessControl, Amount, Channel, L1Summary, L2BuildConfig, L2MergeConfig, Manifest, Metadata,
-    Payment, Visibility,
+    AccessControl, Amount, Channel, Collection, L1Summary, L2BuildConfig, L2MergeConfig, Manifest,
+    Metadata, Payment, Visibility,
 };
 use nodalync_wire::{VersionInfo, VersionSpec};
 
-use crate::error::OpsResult;
+use nodalync_store::{CacheStore, ManifestStore};
+use nodalync_valid::Validator;
+
+use crate::error::{CloseResult, OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::NodeOperations;
 
 /// Response from a query operation.
 #[derive(Debug, Clone)]
@@ -22,6 +40,8 @@ pub struct QueryResponse {
     pub manifest: Manifest,
     /// Payment receipt.
     pub receipt: nodalync_wire::PaymentReceipt,
+    /// Items received and cached with a collection bundle.
+    pub bundle: Vec<Hash>,
 }
 
 /// Response from a preview operation.
@@ -35,19 +55,34 @@ pub struct PreviewResponse {
     /// This is set when content is discovered via announcements, indicating
     /// which peer can serve the content (as opposed to the content owner).
     pub provider_peer_id: Option<String>,
+    /// Item list, when the content is a collection.
+    pub collection: Option<Collection>,
+    /// Other versions the owner published with the same version number.
+    pub forks: Vec<Hash>,
+    /// Paid queries in the pricing rule's demand window, as quoted by the
+    /// owner (0 when unknown).
+    pub window_queries: u64,
 }
 
-/// Main operations trait for the Nodalync protocol.
-///
-/// This trait defines all protocol operations as specified in §7.
-/// Implementations provide the core business logic that orchestrates
-/// the storage, validation, and economic layers.
-#[async_trait]
-pub trait Operations: Send + Sync {
-    // =========================================================================
-    // Content Operations (§7.1)
-    // =========================================================================
+impl PreviewResponse {
+    /// Price charged at `now` under the manifest's pricing rule.
+    pub fn price_at(&self, now: Timestamp) -> Amount {
+        self.manifest.economics.price_at(now, self.window_queries)
+    }
+}
+
+/// State every operation trait relies on.
+pub trait OpsContext: Send + Sync {
+    /// Get the local peer ID.
+    fn my_peer_id(&self) -> PeerId;
+
+    /// Get the current timestamp.
+    fn now(&self) -> Timestamp;
+}
 
+/// Content operations (§7.1) and L2 entity graphs.
+#[async_trait]
+pub trait ContentOps: OpsContext {
     /// Create new L0 content.
     ///
     /// Spec §7.1.1:
@@ -60,7 +95,7 @@ pub trait Operations: Send + Sync {
     /// - Stores content and manifest
     ///
     /// Returns the content hash.
-    async fn create(&mut self, content: &[u8], metadata: Metadata) -> OpsResult<Hash>;
+    async fn create(&self, content: &[u8], metadata: Metadata) -> OpsResult<Hash>;
 
     /// Extract L1 mentions from L0 content.
     ///
@@ -71,29 +106,7 @@ pub trait Operations: Send + Sync {
     /// - Stores L1 data
     ///
     /// Returns the L1 summary.
-    async fn extract_l1(&mut self, hash: &Hash) -> OpsResult<L1Summary>;
-
-    /// Publish content to the network.
-    ///
-    /// Spec §7.1.3:
-    /// - Loads manifest
-    /// - Validates price
-    /// - Updates visibility, price, access_control
-    /// - Saves manifest
-    /// - (DHT announce - stub for MVP)
-    async fn publish(
-        &mut self,
-        hash: &Hash,
-        visibility: Visibility,
-        price: Amount,
-    ) -> OpsResult<()>;
-
-    /// Unpublish content from the network.
-    ///
-    /// Spec §7.1.3:
-    /// - Sets visibility to Private
-    /// - (DHT remove - stub for MVP)
-    async fn unpublish(&mut self, hash: &Hash) -> OpsResult<()>;
+    async fn extract_l1(&self, hash: &Hash) -> OpsResult<L1Summary>;
 
     /// Update existing content.
     ///
@@ -105,7 +118,7 @@ pub trait Operations: Send + Sync {
     ///
     /// Returns the new content hash.
     async fn update(
-        &mut self,
+        &self,
         old_hash: &Hash,
         new_content: &[u8],
         new_metadata: Metadata,
@@ -123,12 +136,8 @@ pub trait Operations: Send + Sync {
     /// - Stores
     ///
     /// Returns the derived content hash.
-    async fn derive(
-        &mut self,
-        sources: &[Hash],
-        insight: &[u8],
-        metadata: Metadata,
-    ) -> OpsResult<Hash>;
+    async fn derive(&self, sources: &[Hash], insight: &[u8], metadata: Metadata)
+        -> OpsResult<Hash>;
 
     /// Reference an L3 as L0 (promotes synthesis to primary source).
     ///
@@ -136,12 +145,82 @@ pub trait Operations: Send + Sync {
     /// - Verifies L3 was queried (in cache)
     /// - Verifies content_type is L3
     /// - Stores reference
-    async fn reference_l3_as_l0(&mut self, l3_hash: &Hash) -> OpsResult<Hash>;
+    async fn reference_l3_as_l0(&self, l3_hash: &Hash) -> OpsResult<Hash>;
 
-    // =========================================================================
-    // Query Operations (§7.2)
-    // =========================================================================
+    /// Build an L2 Entity Graph from L1 sources.
+    ///
+    /// L2 Entity Graphs are personal knowledge graphs that:
+    /// - Are always private (visibility = Private)
+    /// - Have price = 0 (never monetized directly)
+    /// - Enable L3 insights
+    /// - Creators earn through L3 synthesis fees
+    ///
+    /// # Arguments
+    ///
+    /// * `source_l1_hashes` - Hashes of L1 content to build from
+    /// * `config` - Optional build configuration
+    ///
+    /// # Returns
+    ///
+    /// The hash of the created L2 content.
+    async fn build_l2(
+        &self,
+        source_l1_hashes: Vec<Hash>,
+        config: Option<L2BuildConfig>,
+    ) -> OpsResult<Hash>;
+
+    /// Merge multiple L2 Entity Graphs into a single L2.
+    ///
+    /// All source L2s must be owned by the current identity.
+    ///
+    /// # Arguments
+    ///
+    /// * `source_l2_hashes` - Hashes of L2 content to merge
+    /// * `config` - Optional merge configuration
+    ///
+    /// # Returns
+    ///
+    /// The hash of the merged L2 content.
+    async fn merge_l2(
+        &self,
+        source_l2_hashes: Vec<Hash>,
+        config: Option<L2MergeConfig>,
+    ) -> OpsResult<Hash>;
+
+    /// Get a manifest by hash (if owned locally).
+    fn get_manifest(&self, hash: &Hash) -> OpsResult<Option<Manifest>>;
+}
+
+/// Publishing and visibility operations (§7.1.3).
+#[async_trait]
+pub trait PublishOps: OpsContext {
+    /// Publish content to the network.
+    ///
+    /// Spec §7.1.3:
+    /// - Loads manifest
+    /// - Validates price
+    /// - Updates visibility, price, access_control
+    /// - Saves manifest
+    /// - (DHT announce - stub for MVP)
+    async fn publish(&self, hash: &Hash, visibility: Visibility, price: Amount) -> OpsResult<()>;
+
+    /// Unpublish content from the network.
+    ///
+    /// Spec §7.1.3:
+    /// - Sets visibility to Private
+    /// - (DHT remove - stub for MVP)
+    async fn unpublish(&self, hash: &Hash) -> OpsResult<()>;
+
+    /// Set visibility level for content.
+    async fn set_visibility(&self, hash: &Hash, visibility: Visibility) -> OpsResult<()>;
 
+    /// Set access control for content.
+    async fn set_access(&self, hash: &Hash, access: AccessControl) -> OpsResult<()>;
+}
+
+/// Query operations (§7.2).
+#[async_trait]
+pub trait QueryOps: OpsContext {
     /// Preview content metadata and L1 summary.
     ///
     /// Spec §7.2.2:
@@ -164,7 +243,7 @@ pub trait Operations: Send + Sync {
     /// - Updates channel state (debit)
     /// - Caches content
     async fn query(
-        &mut self,
+        &self,
         hash: &Hash,
         payment_amount: Amount,
         version: Option<VersionSpec>,
@@ -177,20 +256,13 @@ pub trait Operations: Send + Sync {
     /// - Converts to VersionInfo
     async fn get_versions(&self, root_hash: &Hash) -> OpsResult<Vec<VersionInfo>>;
 
-    // =========================================================================
-    // Visibility Operations (§7.1.3)
-    // =========================================================================
-
-    /// Set visibility level for content.
-    async fn set_visibility(&mut self, hash: &Hash, visibility: Visibility) -> OpsResult<()>;
-
-    /// Set access control for content.
-    async fn set_access(&mut self, hash: &Hash, access: AccessControl) -> OpsResult<()>;
-
-    // =========================================================================
-    // Channel Operations (§7.3)
-    // =========================================================================
+    /// Check if content was queried (is in cache).
+    fn was_queried(&self, hash: &Hash) -> bool;
+}
 
+/// Payment channel operations (§7.3).
+#[async_trait]
+pub trait ChannelOps: OpsContext {
     /// Open a new payment channel with a peer.
     ///
     /// Spec §7.3.1:
@@ -198,7 +270,7 @@ pub trait Operations: Send + Sync {
     /// - Creates Channel with state=Opening
     /// - Stores locally
     /// - (Send ChannelOpen - stub for MVP)
-    async fn open_channel(&mut self, peer: &PeerId, deposit: Amount) -> OpsResult<Channel>;
+    async fn open_channel(&self, peer: &PeerId, deposit: Amount) -> OpsResult<Channel>;
 
     /// Accept an incoming channel open request.
     ///
@@ -207,7 +279,7 @@ pub trait Operations: Send + Sync {
     /// - Creates reciprocal Channel state
     /// - Stores
     async fn accept_channel(
-        &mut self,
+        &self,
         channel_id: &Hash,
         peer: &PeerId,
         their_deposit: Amount,
@@ -215,7 +287,7 @@ pub trait Operations: Send + Sync {
     ) -> OpsResult<Channel>;
 
     /// Update channel state.
-    async fn update_channel(&mut self, peer: &PeerId, payment: Payment) -> OpsResult<()>;
+    async fn update_channel(&self, peer: &PeerId, payment: Payment) -> OpsResult<()>;
 
     /// Close a payment channel.
     ///
@@ -224,19 +296,19 @@ pub trait Operations: Send + Sync {
     /// - Computes final balances
     /// - (Submit to settlement - stub for MVP)
     /// - Updates state to Closed
-    async fn close_channel(&mut self, peer: &PeerId) -> OpsResult<()>;
+    async fn close_channel(&self, peer: &PeerId) -> OpsResult<()>;
 
     /// Dispute a channel with latest signed state.
     ///
     /// Spec §7.3.4:
     /// - (Submit dispute to chain - stub for MVP)
     /// - Updates state to Disputed
-    async fn dispute_channel(&mut self, peer: &PeerId) -> OpsResult<()>;
-
-    // =========================================================================
-    // Settlement Operations (§7.5)
-    // =========================================================================
+    async fn dispute_channel(&self, peer: &PeerId) -> OpsResult<()>;
+}
 
+/// Settlement operations (§7.5).
+#[async_trait]
+pub trait SettlementOps: OpsContext {
     /// Trigger settlement batch.
     ///
     /// Spec §7.5:
@@ -246,65 +318,251 @@ pub trait Operations: Send + Sync {
     /// - (Submit to chain - stub for MVP)
     /// - Marks as settled
     /// - Updates last_settlement_time
-    async fn trigger_settlement(&mut self) -> OpsResult<Option<Hash>>;
+    async fn trigger_settlement(&self) -> OpsResult<Option<Hash>>;
+}
 
-    // =========================================================================
-    // L2 Entity Graph Operations
-    // =========================================================================
+/// Main operations trait for the Nodalync protocol.
+///
+/// Combines every capability trait, so it defines all protocol operations
+/// as specified in §7. It is implemented for any type implementing all of
+/// them; partial implementations implement just the traits they need.
+pub trait Operations: ContentOps + PublishOps + QueryOps + ChannelOps + SettlementOps {}
+
+impl<T> Operations for T where T: ContentOps + PublishOps + QueryOps + ChannelOps + SettlementOps {}
+
+// =============================================================================
+// NodeOperations
+// =============================================================================
+
+impl<V, E> OpsContext for NodeOperations<V, E>
+where
+    V: Validator + Send + Sync,
+    E: L1Extractor + Send + Sync,
+{
+    fn my_peer_id(&self) -> PeerId {
+        self.peer_id()
+    }
+
+    fn now(&self) -> Timestamp {
+        self.network_time()
+    }
+}
+
+#[async_trait]
+impl<V, E> ContentOps for NodeOperations<V, E>
+where
+    V: Validator + Send + Sync,
+    E: L1Extractor + Send + Sync,
+{
+    async fn create(&self, content: &[u8], metadata: Metadata) -> OpsResult<Hash> {
+        self.create_content(content, metadata)
+    }
+
+    async fn extract_l1(&self, hash: &Hash) -> OpsResult<L1Summary> {
+        self.extract_l1_summary(hash)
+    }
+
+    async fn update(
+        &self,
+        old_hash: &Hash,
+        new_content: &[u8],
+        new_metadata: Metadata,
+    ) -> OpsResult<Hash> {
+        self.update_content(old_hash, new_content, new_metadata)
+    }
+
+    async fn derive(
+        &self,
+        sources: &[Hash],
+        insight: &[u8],
+        metadata: Metadata,
+    ) -> OpsResult<Hash> {
+        self.derive_content(sources, insight, metadata)
+    }
+
+    async fn reference_l3_as_l0(&self, l3_hash: &Hash) -> OpsResult<Hash> {
+        NodeOperations::reference_l3_as_l0(self, l3_hash)
+    }
 
-    /// Build an L2 Entity Graph from L1 sources.
-    ///
-    /// L2 Entity Graphs are personal knowledge graphs that:
-    /// - Are always private (visibility = Private)
-    /// - Have price = 0 (never monetized directly)
-    /// - Enable L3 insights
-    /// - Creators earn through L3 synthesis fees
-    ///
-    /// # Arguments
-    ///
-    /// * `source_l1_hashes` - Hashes of L1 content to build from
-    /// * `config` - Optional build configuration
-    ///
-    /// # Returns
-    ///
-    /// The hash of the created L2 content.
     async fn build_l2(
-        &mut self,
+        &self,
         source_l1_hashes: Vec<Hash>,
         config: Option<L2BuildConfig>,
-    ) -> OpsResult<Hash>;
+    ) -> OpsResult<Hash> {
+        NodeOperations::build_l2(self, source_l1_hashes, config)
+    }
 
-    /// Merge multiple L2 Entity Graphs into a single L2.
-    ///
-    /// All source L2s must be owned by the current identity.
-    ///
-    /// # Arguments
-    ///
-    /// * `source_l2_hashes` - Hashes of L2 content to merge
-    /// * `config` - Optional merge configuration
-    ///
-    /// # Returns
-    ///
-    /// The hash of the merged L2 content.
     async fn merge_l2(
-        &mut self,
+        &self,
         source_l2_hashes: Vec<Hash>,
         config: Option<L2MergeConfig>,
-    ) -> OpsResult<Hash>;
+    ) -> OpsResult<Hash> {
+        NodeOperations::merge_l2(self, source_l2_hashes, config)
+    }
 
-    // =========================================================================
-    // Utility Methods
-    // =========================================================================
+    fn get_manifest(&self, hash: &Hash) -> OpsResult<Option<Manifest>> {
+        Ok(self.state.manifests.load(hash)?)
+    }
+}
 
-    /// Get the local peer ID.
-    fn my_peer_id(&self) -> PeerId;
+#[async_trait]
+impl<V, E> PublishOps for NodeOperations<V, E>
+where
+    V: Validator + Send + Sync,
+    E: L1Extractor + Send + Sync,
+{
+    async fn publish(&self, hash: &Hash, visibility: Visibility, price: Amount) -> OpsResult<()> {
+        self.publish_content(hash, visibility, price).await
+    }
+
+    async fn unpublish(&self, hash: &Hash) -> OpsResult<()> {
+        self.unpublish_content(hash).await
+    }
+
+    async fn set_visibility(&self, hash: &Hash, visibility: Visibility) -> OpsResult<()> {
+        self.set_content_visibility(hash, visibility)
+    }
+
+    async fn set_access(&self, hash: &Hash, access: AccessControl) -> OpsResult<()> {
+        self.set_content_access(hash, access)
+    }
+}
 
-    /// Get the current timestamp.
-    fn now(&self) -> Timestamp;
+#[async_trait]
+impl<V, E> QueryOps for NodeOperations<V, E>
+where
+    V: Validator + Send + Sync,
+    E: L1Extractor + Send + Sync,
+{
+    async fn preview(&self, hash: &Hash) -> OpsResult<PreviewResponse> {
+        self.preview_content(hash).await
+    }
 
-    /// Get a manifest by hash (if owned locally).
-    fn get_manifest(&self, hash: &Hash) -> OpsResult<Option<Manifest>>;
+    async fn query(
+        &self,
+        hash: &Hash,
+        payment_amount: Amount,
+        version: Option<VersionSpec>,
+    ) -> OpsResult<QueryResponse> {
+        self.query_content(hash, payment_amount, version).await
+    }
 
-    /// Check if content was queried (is in cache).
-    fn was_queried(&self, hash: &Hash) -> bool;
+    async fn get_versions(&self, root_hash: &Hash) -> OpsResult<Vec<VersionInfo>> {
+        self.get_content_versions(root_hash)
+    }
+
+    fn was_queried(&self, hash: &Hash) -> bool {
+        self.state.cache.is_cached(hash)
+    }
+}
+
+#[async_trait]
+impl<V, E> ChannelOps for NodeOperations<V, E>
+where
+    V: Validator + Send + Sync,
+    E: L1Extractor + Send + Sync,
+{
+    async fn open_channel(&self, peer: &PeerId, deposit: Amount) -> OpsResult<Channel> {
+        self.open_payment_channel(peer, deposit).await
+    }
+
+    async fn accept_channel(
+        &self,
+        channel_id: &Hash,
+        peer: &PeerId,
+        their_deposit: Amount,
+        my_deposit: Amount,
+    ) -> OpsResult<Channel> {
+        self.accept_payment_channel(channel_id, peer, their_deposit, my_deposit)
+    }
+
+    async fn update_channel(&self, peer: &PeerId, payment: Payment) -> OpsResult<()> {
+        self.update_payment_channel(peer, payment)
+    }
+
+    /// Closes cooperatively with our key. An unresponsive peer leaves the
+    /// close pending and is an error.
+    async fn close_channel(&self, peer: &PeerId) -> OpsResult<()> {
+        let private_key = self
+            .private_key()
+            .ok_or(OpsError::PrivateKeyRequired)?
+            .clone();
+        match self.close_payment_channel(peer, &private_key).await? {
+            CloseResult::Success { .. } | CloseResult::SuccessOffChain { .. } => Ok(()),
+            CloseResult::PeerUnresponsive { suggestion } => {
+                Err(OpsError::invalid_operation(suggestion))
+            }
+            CloseResult::OnChainFailed { error } => Err(OpsError::SettlementFailed(error)),
+        }
+    }
+
+    async fn dispute_channel(&self, peer: &PeerId) -> OpsResult<()> {
+        let private_key = self
+            .private_key()
+            .ok_or(OpsError::PrivateKeyRequired)?
+            .clone();
+        self.dispute_payment_channel(peer, &private_key).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<V, E> SettlementOps for NodeOperations<V, E>
+where
+    V: Validator + Send + Sync,
+    E: L1Extractor + Send + Sync,
+{
+    async fn trigger_settlement(&self) -> OpsResult<Option<Hash>> {
+        self.trigger_settlement_batch().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OpsConfig;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops = DefaultNodeOperations::with_config(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default(),
+        );
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    /// A consumer that only previews, written against just [`QueryOps`].
+    async fn preview_title<T: QueryOps + ?Sized>(ops: &T, hash: &Hash) -> String {
+        ops.preview(hash).await.unwrap().manifest.metadata.title
+    }
+
+    #[tokio::test]
+    async fn test_node_operations_through_capability_traits() {
+        let (ops, _temp) = create_test_ops();
+        let content = b"Capability traits";
+        let hash = ContentOps::create(&ops, content, Metadata::new("Traits", content.len() as u64))
+            .await
+            .unwrap();
+        assert_eq!(ops.get_manifest(&hash).unwrap().unwrap().hash, hash);
+
+        PublishOps::publish(&ops, &hash, Visibility::Shared, 0)
+            .await
+            .unwrap();
+        assert_eq!(preview_title(&ops, &hash).await, "Traits");
+
+        // The combined trait works as a trait object
+        let combined: &dyn Operations = &ops;
+        assert_eq!(combined.my_peer_id(), ops.peer_id());
+        assert_eq!(combined.get_versions(&hash).await.unwrap().len(), 1);
+        assert!(!combined.was_queried(&hash));
+        assert_eq!(combined.trigger_settlement().await.unwrap(), None);
+    }
 }
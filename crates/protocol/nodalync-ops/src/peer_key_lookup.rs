@@ -1,26 +1,26 @@
 //! Peer store-backed public key lookup.
 //!
-//! Bridges `SqlitePeerStore` with the `PublicKeyLookup` trait from `nodalync-valid`,
+//! Bridges the node's peer store with the `PublicKeyLookup` trait from `nodalync-valid`,
 //! enabling signature verification against keys stored in the peer database.
 
 use nodalync_crypto::{PeerId, PublicKey};
-use nodalync_store::{NodeState, SqlitePeerStore};
+use nodalync_store::{NodeState, PeerBackend};
 use nodalync_valid::PublicKeyLookup;
 
-/// Public key lookup backed by the SQLite peer store.
+/// Public key lookup backed by the peer store.
 ///
-/// Wraps a `SqlitePeerStore` to implement `PublicKeyLookup` for use in
+/// Wraps a `PeerBackend` to implement `PublicKeyLookup` for use in
 /// validators and signature verification. Returns `None` for unknown peers
 /// or peers with all-zero (unset) public keys.
 pub struct PeerStoreKeyLookup {
-    peers: SqlitePeerStore,
+    peers: PeerBackend,
 }
 
 impl PeerStoreKeyLookup {
-    /// Create a new lookup from a `NodeState`'s shared database connection.
+    /// Create a new lookup from a `NodeState`'s peer store.
     pub fn from_state(state: &NodeState) -> Self {
         Self {
-            peers: SqlitePeerStore::new(state.connection()),
+            peers: state.peers.clone(),
         }
     }
 }
@@ -64,7 +64,7 @@ mod tests {
 
     #[test]
     fn test_lookup_returns_pubkey_after_registration() {
-        let (lookup, mut state, _temp) = setup();
+        let (lookup, state, _temp) = setup();
         let (_, public_key) = generate_identity();
         let peer_id = peer_id_from_public_key(&public_key);
 
@@ -80,7 +80,7 @@ mod tests {
 
     #[test]
     fn test_lookup_returns_none_for_zero_pubkey() {
-        let (lookup, mut state, _temp) = setup();
+        let (lookup, state, _temp) = setup();
         let (_, public_key) = generate_identity();
         let peer_id = peer_id_from_public_key(&public_key);
 
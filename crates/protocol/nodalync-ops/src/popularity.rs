@@ -0,0 +1,296 @@
+//! Content popularity and cache prewarming.
+//!
+//! Previews and queries we serve, searches our content matches, and
+//! queries made locally are counted per hash into a decaying score (see
+//! [`PopularityConfig`](crate::PopularityConfig)). [`prewarm_cache`](NodeOperations::prewarm_cache)
+//! uses the scores to keep popular content fast to serve: the most popular
+//! manifests are held in memory, popular cached content is refreshed so
+//! LRU eviction and the cache TTL pass it over, and, if enabled, popular
+//! free content we don't hold is fetched ahead of the next request.
+
+use nodalync_crypto::Hash;
+use nodalync_store::{CacheStore, ContentStore, PopularityKind, PopularityRecord, PopularityStore};
+use nodalync_valid::Validator;
+use tracing::{debug, info, warn};
+
+use crate::error::OpsResult;
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+/// What a prewarm run did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrewarmReport {
+    /// Hashes scoring at least the popularity threshold.
+    pub popular: usize,
+    /// Manifests now held in memory.
+    pub hot_manifests: usize,
+    /// Popular cached items refreshed against eviction.
+    pub retained: usize,
+    /// Popular free items fetched into the cache.
+    pub fetched: usize,
+    /// Fetches that failed.
+    pub failed: usize,
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// The most popular content hashes now, most popular first.
+    ///
+    /// Scores are decayed to the current time.
+    pub fn popular_content(&self, limit: usize) -> OpsResult<Vec<PopularityRecord>> {
+        Ok(self.state.popularity.top(
+            limit,
+            self.config.popularity.half_life_ms,
+            current_timestamp(),
+        )?)
+    }
+
+    /// Keep popular content fast to serve.
+    ///
+    /// 1. Hold the manifests of the `hot_manifests` most popular hashes in
+    ///    memory, replacing the previous set
+    /// 2. Refresh popular cached content so it is evicted last
+    /// 3. With `fetch` set, query up to `max_fetch` popular hashes we don't
+    ///    hold whose announcements are free; these queries don't count
+    ///    towards popularity
+    ///
+    /// Only hashes scoring at least `min_score` count as popular.
+    pub async fn prewarm_cache(&self) -> OpsResult<PrewarmReport> {
+        let config = self.config.popularity.clone();
+        let now = current_timestamp();
+        let popular: Vec<Hash> = self
+            .state
+            .popularity
+            .top(usize::MAX, config.half_life_ms, now)?
+            .into_iter()
+            .take_while(|record| record.score >= config.min_score)
+            .map(|record| record.hash)
+            .collect();
+
+        let mut report = PrewarmReport {
+            popular: popular.len(),
+            ..Default::default()
+        };
+
+        let hot: Vec<Hash> = popular.iter().take(config.hot_manifests).copied().collect();
+        report.hot_manifests = self.state.manifests.retain_hot(&hot)?;
+
+        for hash in &popular {
+            if self.state.cache.is_cached(hash) {
+                self.state.cache.touch(hash, now)?;
+                report.retained += 1;
+            }
+        }
+
+        if config.fetch && self.network().is_some() {
+            for hash in &popular {
+                if report.fetched + report.failed >= config.max_fetch {
+                    break;
+                }
+                if self.state.content.exists(hash) || self.state.cache.is_cached(hash) {
+                    continue;
+                }
+                let free = self
+                    .state
+                    .get_announcement(hash)
+                    .is_some_and(|announcement| announcement.price == 0);
+                if !free {
+                    continue;
+                }
+                match self.fetch_content(hash, 0, None).await {
+                    Ok(_) => report.fetched += 1,
+                    Err(e) => {
+                        debug!(hash = %hash, error = %e, "Failed to prewarm content");
+                        report.failed += 1;
+                    }
+                }
+            }
+        }
+
+        if report.fetched > 0 {
+            info!(
+                fetched = report.fetched,
+                hot_manifests = report.hot_manifests,
+                "Prewarmed popular content"
+            );
+        }
+        Ok(report)
+    }
+
+    /// Count a request towards a content hash's popularity.
+    ///
+    /// Like analytics, this must never fail a request, so errors are only
+    /// logged.
+    pub(crate) fn record_popularity(&self, hash: &Hash, kind: PopularityKind) {
+        if !self.config.popularity.enabled {
+            return;
+        }
+        if let Err(e) = self.state.popularity.record(
+            hash,
+            kind,
+            self.config.popularity.half_life_ms,
+            current_timestamp(),
+        ) {
+            warn!(hash = %hash, error = %e, "Failed to record content popularity");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{OpsConfig, PopularityConfig};
+    use crate::{DefaultNodeOperations, Network};
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key, PeerId};
+    use nodalync_store::{ManifestStore, NodeState};
+    use nodalync_test_utils::MockNetwork;
+    use nodalync_types::{ContentType, L1Summary, Metadata, Visibility};
+    use nodalync_wire::{AnnouncePayload, PreviewRequestPayload, QueryRequestPayload};
+    use std::sync::Arc;
+
+    fn create_test_ops(config: PopularityConfig) -> DefaultNodeOperations {
+        let state = NodeState::open_in_memory().unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops = DefaultNodeOperations::with_config(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default().with_popularity(config),
+        );
+        ops.set_private_key(private_key);
+        ops
+    }
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    async fn publish(ops: &mut DefaultNodeOperations, content: &[u8]) -> Hash {
+        let hash = ops
+            .create_content(content, Metadata::new("Notes", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 0)
+            .await
+            .unwrap();
+        hash
+    }
+
+    #[tokio::test]
+    async fn test_served_requests_count() {
+        let mut ops = create_test_ops(PopularityConfig::default());
+        let hash = publish(&mut ops, b"Popular notes").await;
+        let quiet = publish(&mut ops, b"Quiet notes").await;
+        let requester = test_peer_id();
+
+        ops.handle_preview_request(&requester, &PreviewRequestPayload { hash })
+            .unwrap();
+        ops.handle_query_request(
+            &requester,
+            &QueryRequestPayload {
+                hash,
+                query: None,
+                payment: None,
+                version_spec: None,
+                payment_nonce: 0,
+                capability: None,
+                recipient_key: None,
+                challenge_response: None,
+                request_id: None,
+                section: None,
+                unit: None,
+                referrer: None,
+                entitlement: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let record = ops.state.popularity.get(&hash).unwrap().unwrap();
+        assert_eq!((record.previews, record.queries), (1, 1));
+        let top = ops.popular_content(10).unwrap();
+        assert_eq!(top[0].hash, hash);
+        assert!(ops.state.popularity.get(&quiet).unwrap().is_none());
+
+        // Disabled, nothing is counted
+        let mut off = create_test_ops(PopularityConfig::default().with_enabled(false));
+        let hash = publish(&mut off, b"Uncounted notes").await;
+        off.handle_preview_request(&requester, &PreviewRequestPayload { hash })
+            .unwrap();
+        assert!(off.popular_content(10).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_prewarm_holds_hot_manifests() {
+        let mut ops = create_test_ops(
+            PopularityConfig::default()
+                .with_hot_manifests(1)
+                .with_min_score(1.0),
+        );
+        let first = publish(&mut ops, b"First").await;
+        let second = publish(&mut ops, b"Second").await;
+        let third = publish(&mut ops, b"Third").await;
+        for (hash, previews) in [(first, 3), (second, 2), (third, 0)] {
+            for _ in 0..previews {
+                ops.record_popularity(&hash, PopularityKind::Preview);
+            }
+        }
+
+        let report = ops.prewarm_cache().await.unwrap();
+        assert_eq!(report.popular, 2);
+        assert_eq!(report.hot_manifests, 1);
+        assert!(ops.state.manifests.is_hot(&first));
+        assert!(!ops.state.manifests.is_hot(&second));
+        assert!(ops.state.manifests.load(&first).unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_prewarm_fetches_free_content() {
+        let mut ops = create_test_ops(PopularityConfig::default().with_fetch(2));
+        ops.set_network(Arc::new(MockNetwork::new()) as Arc<dyn Network>);
+
+        let announce = |title: &str, price| {
+            let hash = nodalync_crypto::content_hash(title.as_bytes());
+            AnnouncePayload {
+                hash,
+                content_type: ContentType::L0,
+                title: title.to_string(),
+                l1_summary: L1Summary::empty(hash),
+                price,
+                addresses: vec![],
+                publisher_peer_id: None,
+                publisher_key: None,
+                signature: None,
+                delegation: None,
+            }
+        };
+        let free = announce("Free", 0);
+        let paid = announce("Paid", 100);
+        for announcement in [&free, &paid] {
+            ops.state.store_announcement(announcement.clone());
+            ops.record_popularity(&announcement.hash, PopularityKind::Query);
+        }
+
+        // Only the free item is tried; with no provider it fails, and the
+        // attempt isn't counted as a query
+        let report = ops.prewarm_cache().await.unwrap();
+        assert_eq!(report.popular, 2);
+        assert_eq!((report.fetched, report.failed), (0, 1));
+        assert_eq!(
+            ops.state
+                .popularity
+                .get(&free.hash)
+                .unwrap()
+                .unwrap()
+                .queries,
+            1
+        );
+
+        // Without fetching, nothing is tried
+        ops.config.popularity.fetch = false;
+        let report = ops.prewarm_cache().await.unwrap();
+        assert_eq!((report.fetched, report.failed), (0, 0));
+    }
+}
@@ -0,0 +1,516 @@
+//! Price suggestions for content about to be published, and current prices
+//! under dynamic pricing rules.
+//!
+//! Suggestions gather comparables for `nodalync_econ::suggest_price`:
+//!
+//! - **Announcements** stored from the network, weighted by how many of
+//!   their title keywords and primary topics the new content shares in its
+//!   title and tags
+//! - **Our own shared content**, weighted the same way and also by how
+//!   close its size is, since announcements don't carry one
+//!
+//! Every paid comparable counts a little (`MARKET_WEIGHT`) even when nothing
+//! matches, so there is a suggestion whenever the node has seen any prices;
+//! content of another type counts `OTHER_TYPE_WEIGHT` as much. Previews and
+//! paid queries of our own content that is at least `MIN_DEMAND_SIMILARITY`
+//! similar give the conversion rate the range is adjusted by.
+//!
+//! Content with a `PricingRule` charges `Economics::price_at`. Surge prices
+//! depend on the paid queries in the previous window, which only the owner's
+//! access log knows; previews quote that count so consumers compute the same
+//! price. The owner accepts the lowest price within `DYNAMIC_PRICE_GRACE_MS`
+//! either side of its clock, so a payment signed just before a window
+//! boundary or with a slightly skewed clock still goes through.
+
+use std::collections::HashSet;
+
+use nodalync_crypto::Timestamp;
+use nodalync_econ::{
+    suggest_price, validate_pricing_rule, DemandStats, PricePoint, PriceSuggestion,
+};
+use nodalync_store::{AccessKind, AccessLogStore, ManifestFilter, ManifestStore};
+use nodalync_types::{Amount, ContentType, Manifest, Metadata, Visibility, DYNAMIC_PRICE_GRACE_MS};
+use nodalync_valid::Validator;
+use nodalync_wire::PreviewRequestPayload;
+
+use crate::analytics::ContentStats;
+use crate::error::OpsResult;
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+use crate::ops::PreviewResponse;
+use crate::recommend::{keywords, normalize};
+
+/// Weight every paid comparable gets on top of its similarity.
+pub const MARKET_WEIGHT: f64 = 0.05;
+
+/// Relative weight of comparables of another content type.
+pub const OTHER_TYPE_WEIGHT: f64 = 0.25;
+
+/// Similarity our own content needs for its demand to count.
+pub const MIN_DEMAND_SIMILARITY: f64 = 0.1;
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Suggest a price range for new content from market data.
+    ///
+    /// Returns `None` when the node knows no paid content to compare with.
+    pub fn suggest_price(
+        &self,
+        content_type: ContentType,
+        metadata: &Metadata,
+    ) -> OpsResult<Option<PriceSuggestion>> {
+        let terms = content_terms(&metadata.title, &metadata.tags);
+        let type_weight = |other: ContentType| {
+            if other == content_type {
+                1.0
+            } else {
+                OTHER_TYPE_WEIGHT
+            }
+        };
+
+        let mut points = Vec::new();
+        let mut demand = DemandStats::default();
+        let mut own = HashSet::new();
+
+        let filter = ManifestFilter::new()
+            .with_owner(self.peer_id())
+            .with_visibility(Visibility::Shared);
+        for manifest in self.state.manifests.list(filter)? {
+            own.insert(manifest.hash);
+            if manifest.economics.price == 0 {
+                continue;
+            }
+
+            let similarity = jaccard(
+                &terms,
+                &content_terms(&manifest.metadata.title, &manifest.metadata.tags),
+            );
+            let size = size_weight(metadata.content_size, manifest.metadata.content_size);
+            points.push(PricePoint::new(
+                manifest.economics.price,
+                (MARKET_WEIGHT + similarity) * type_weight(manifest.content_type) * size,
+            ));
+
+            if similarity >= MIN_DEMAND_SIMILARITY {
+                let records = self.state.access_log.for_content(&manifest.hash)?;
+                let stats = ContentStats::from_records(manifest.hash, &records);
+                demand.previews += stats.previews;
+                demand.paid_queries += stats.queries - stats.free_queries;
+            }
+        }
+
+        for announcement in self.state.list_announcements() {
+            if own.contains(&announcement.hash) {
+                continue;
+            }
+            let mut announced: HashSet<String> =
+                keywords(&announcement.title).into_iter().collect();
+            announced.extend(
+                announcement
+                    .l1_summary
+                    .primary_topics
+                    .iter()
+                    .map(|t| normalize(t))
+                    .filter(|t| !t.is_empty()),
+            );
+            points.push(PricePoint::new(
+                announcement.price,
+                (MARKET_WEIGHT + jaccard(&terms, &announced))
+                    * type_weight(announcement.content_type),
+            ));
+        }
+
+        Ok(suggest_price(&points, &demand))
+    }
+
+    /// Paid queries of our content in its pricing rule's demand window at
+    /// `now`, 0 when the rule doesn't use one.
+    ///
+    /// Counts the access log, so surge pricing needs analytics enabled.
+    pub fn window_queries(&self, manifest: &Manifest, now: Timestamp) -> OpsResult<u64> {
+        let Some(window) = manifest
+            .economics
+            .pricing
+            .and_then(|rule| rule.demand_window(now))
+        else {
+            return Ok(0);
+        };
+
+        let records = self.state.access_log.for_content(&manifest.hash)?;
+        Ok(records
+            .iter()
+            .filter(|r| r.kind == AccessKind::Query && r.amount > 0)
+            .filter(|r| window.contains(&r.timestamp))
+            .count() as u64)
+    }
+
+    /// Price our content charges at `now`.
+    pub fn current_price(&self, manifest: &Manifest, now: Timestamp) -> OpsResult<Amount> {
+        let window_queries = self.window_queries(manifest, now)?;
+        Ok(manifest.economics.price_at(now, window_queries))
+    }
+
+    /// Smallest payment accepted for our content at `now`: the lowest price
+    /// within the grace period either side.
+    pub(crate) fn minimum_accepted_price(
+        &self,
+        manifest: &Manifest,
+        now: Timestamp,
+    ) -> OpsResult<Amount> {
+        if manifest.economics.pricing.is_none() {
+            return Ok(manifest.economics.price);
+        }
+
+        let mut minimum = self.current_price(manifest, now)?;
+        for at in [
+            now.saturating_sub(DYNAMIC_PRICE_GRACE_MS),
+            now + DYNAMIC_PRICE_GRACE_MS,
+        ] {
+            minimum = minimum.min(self.current_price(manifest, at)?);
+        }
+        Ok(minimum)
+    }
+
+    /// Price to pay for previewed content right now.
+    ///
+    /// Announcements carry only the base price, so for remote content with
+    /// a known provider this asks it for a fresh preview, whose manifest
+    /// holds the pricing rule and whose window count sets a surge price.
+    /// Falls back to the preview's own price, also when the fresh manifest's
+    /// pricing rule is invalid.
+    pub async fn quote_price(&self, preview: &PreviewResponse) -> Amount {
+        let now = current_timestamp();
+        let hash = preview.manifest.hash;
+
+        if preview.manifest.owner == self.peer_id() {
+            return self
+                .current_price(&preview.manifest, now)
+                .unwrap_or_else(|_| preview.price_at(now));
+        }
+
+        let provider = preview
+            .provider_peer_id
+            .as_deref()
+            .and_then(|p| p.parse::<nodalync_net::PeerId>().ok());
+        if let (Some(network), Some(provider)) = (self.network(), provider) {
+            if let Ok(remote) = network
+                .send_preview_request(provider, PreviewRequestPayload { hash })
+                .await
+            {
+                if remote.manifest.hash == hash && check_remote_pricing(&remote.manifest).is_ok() {
+                    return remote
+                        .manifest
+                        .economics
+                        .price_at(now, remote.window_queries.unwrap_or(0));
+                }
+            }
+        }
+
+        preview.price_at(now)
+    }
+}
+
+/// Check the pricing rule in a manifest from another peer before pricing
+/// with it, as publishing would have.
+pub(crate) fn check_remote_pricing(manifest: &Manifest) -> OpsResult<()> {
+    match &manifest.economics.pricing {
+        Some(rule) => Ok(validate_pricing_rule(manifest.economics.price, rule)?),
+        None => Ok(()),
+    }
+}
+
+/// Normalized title keywords and tags. Hierarchical tags also add each of
+/// their levels, so `science/biology` matches a `biology` topic.
+fn content_terms(title: &str, tags: &[String]) -> HashSet<String> {
+    let mut terms: HashSet<String> = keywords(title).into_iter().collect();
+    for tag in tags {
+        terms.insert(normalize(tag));
+        terms.extend(tag.split('/').map(normalize));
+    }
+    terms.remove("");
+    terms
+}
+
+/// Shared terms over all terms, 0 when either side has none.
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// Between 0.5 for very different sizes and 1 for equal ones.
+fn size_weight(a: u64, b: u64) -> f64 {
+    let (small, large) = (a.min(b), a.max(b));
+    if large == 0 {
+        return 1.0;
+    }
+    0.5 + 0.5 * small as f64 / large as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::OpsError;
+    use crate::node_ops::{current_timestamp, DefaultNodeOperations};
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key, Hash};
+    use nodalync_econ::EconError;
+    use nodalync_store::{AccessKind, AccessRecord, AccessRequester, NodeStateConfig};
+    use nodalync_types::{L1Summary, PricingRule};
+    use nodalync_wire::AnnouncePayload;
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let config = NodeStateConfig::new(temp_dir.path());
+        let state = nodalync_store::NodeState::open(config).unwrap();
+
+        let (_, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+
+        let ops = DefaultNodeOperations::with_defaults(state, peer_id);
+        (ops, temp_dir)
+    }
+
+    fn announce(ops: &DefaultNodeOperations, title: &str, topics: &[&str], price: Amount) {
+        let hash = content_hash(title.as_bytes());
+        let mut l1_summary = L1Summary::empty(hash);
+        l1_summary.primary_topics = topics.iter().map(|t| t.to_string()).collect();
+        ops.state.store_announcement(AnnouncePayload {
+            hash,
+            content_type: ContentType::L0,
+            title: title.to_string(),
+            l1_summary,
+            price,
+            addresses: vec![],
+            publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
+        });
+    }
+
+    fn publish_own(ops: &DefaultNodeOperations, title: &str, tags: &[&str], price: Amount) -> Hash {
+        let hash = content_hash(title.as_bytes());
+        let metadata =
+            Metadata::new(title, 1_000).with_tags(tags.iter().map(|t| t.to_string()).collect());
+        let mut manifest = Manifest::new_l0(hash, ops.peer_id(), metadata, current_timestamp());
+        manifest.visibility = Visibility::Shared;
+        manifest.economics.price = price;
+        ops.state.manifests.store(&manifest).unwrap();
+        hash
+    }
+
+    fn access(ops: &DefaultNodeOperations, hash: Hash, kind: AccessKind, count: u64) {
+        for i in 0..count {
+            let record = AccessRecord::new(
+                hash,
+                AccessRequester::Hashed(content_hash(&i.to_be_bytes())),
+                kind,
+                if kind == AccessKind::Query { 100 } else { 0 },
+                current_timestamp(),
+            );
+            ops.state.access_log.record(&record).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_content_terms() {
+        let terms = content_terms(
+            "Protein Folding Notes",
+            &["science/biology".to_string(), "ML".to_string()],
+        );
+        for term in [
+            "protein",
+            "folding",
+            "science biology",
+            "science",
+            "biology",
+            "ml",
+        ] {
+            assert!(terms.contains(term), "missing {}", term);
+        }
+        assert!(!terms.contains("notes"));
+    }
+
+    #[test]
+    fn test_no_market_data() {
+        let (ops, _temp) = create_test_ops();
+        let metadata = Metadata::new("Protein Folding", 1_000);
+        assert!(ops
+            .suggest_price(ContentType::L0, &metadata)
+            .unwrap()
+            .is_none());
+
+        // Free content says nothing about prices
+        announce(&ops, "Free Protein Guide", &["protein"], 0);
+        assert!(ops
+            .suggest_price(ContentType::L0, &metadata)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_similar_content_dominates() {
+        let (ops, _temp) = create_test_ops();
+        announce(&ops, "Protein Folding Survey", &["biology", "protein"], 200);
+        announce(&ops, "Protein Structure Atlas", &["biology"], 300);
+        for i in 0..5 {
+            announce(
+                &ops,
+                &format!("Football Results {}", i),
+                &["sports"],
+                10_000,
+            );
+        }
+
+        let metadata = Metadata::new("Protein Folding Methods", 1_000)
+            .with_tags(vec!["science/biology".to_string()]);
+        let suggestion = ops
+            .suggest_price(ContentType::L0, &metadata)
+            .unwrap()
+            .unwrap();
+        assert_eq!(suggestion.comparables, 7);
+        assert_eq!(suggestion.low, 200);
+        assert_eq!(suggestion.median, 300);
+        assert_eq!(suggestion.high, 300);
+        assert_eq!(suggestion.conversion_rate, None);
+    }
+
+    #[test]
+    fn test_demand_adjusts_range() {
+        let (ops, _temp) = create_test_ops();
+        announce(&ops, "Protein Folding Survey", &["protein"], 400);
+
+        // Our similar content is previewed a lot but never bought
+        let hash = publish_own(&ops, "Protein Folding Basics", &["biology"], 400);
+        access(&ops, hash, AccessKind::Preview, 20);
+
+        let metadata = Metadata::new("Protein Folding Methods", 1_000);
+        let suggestion = ops
+            .suggest_price(ContentType::L0, &metadata)
+            .unwrap()
+            .unwrap();
+        assert_eq!(suggestion.comparables, 2);
+        assert_eq!(suggestion.conversion_rate, Some(0.0));
+        assert!(suggestion.median < 400);
+
+        // Unrelated content's demand doesn't count
+        let other = publish_own(&ops, "Football Results", &["sports"], 400);
+        access(&ops, other, AccessKind::Query, 20);
+        let suggestion = ops
+            .suggest_price(ContentType::L0, &metadata)
+            .unwrap()
+            .unwrap();
+        assert_eq!(suggestion.conversion_rate, Some(0.0));
+    }
+
+    #[test]
+    fn test_surge_price() {
+        let (ops, _temp) = create_test_ops();
+        let hash = publish_own(&ops, "Live Scores", &["sports"], 1_000);
+        let rule = PricingRule::Surge {
+            max_price: 2_000,
+            window_ms: 600_000,
+            step_bps: 1_000,
+        };
+        ops.set_content_pricing(&hash, Some(rule)).unwrap();
+        let manifest = ops.state.manifests.load(&hash).unwrap().unwrap();
+
+        // Three paid queries and a preview in the previous window, one paid
+        // query in the current one
+        let now = 10 * 600_000 + 5_000;
+        for (kind, amount, timestamp) in [
+            (AccessKind::Query, 1_000, 9 * 600_000u64),
+            (AccessKind::Query, 1_000, 9 * 600_000 + 1),
+            (AccessKind::Query, 1_000, 10 * 600_000 - 1),
+            (AccessKind::Preview, 0, 9 * 600_000 + 2),
+            (AccessKind::Query, 1_000, 10 * 600_000),
+        ] {
+            let record = AccessRecord::new(
+                hash,
+                AccessRequester::Hashed(content_hash(&timestamp.to_be_bytes())),
+                kind,
+                amount,
+                timestamp,
+            );
+            ops.state.access_log.record(&record).unwrap();
+        }
+
+        assert_eq!(ops.window_queries(&manifest, now).unwrap(), 3);
+        assert_eq!(ops.current_price(&manifest, now).unwrap(), 1_300);
+
+        // Within the grace period of the window start, the old window's
+        // (empty) count is accepted too
+        assert_eq!(ops.minimum_accepted_price(&manifest, now).unwrap(), 1_000);
+        let later = now + 2 * DYNAMIC_PRICE_GRACE_MS;
+        assert_eq!(ops.minimum_accepted_price(&manifest, later).unwrap(), 1_300);
+    }
+
+    #[test]
+    fn test_set_content_pricing() {
+        let (ops, _temp) = create_test_ops();
+        let hash = publish_own(&ops, "Market Feed", &["finance"], 1_000);
+
+        // The bounds must be above the base price
+        let below = PricingRule::Decay {
+            start_price: 500,
+            starts_at: 0,
+            duration_ms: 1_000,
+        };
+        assert!(ops.set_content_pricing(&hash, Some(below)).is_err());
+
+        let rule = PricingRule::Decay {
+            start_price: 5_000,
+            starts_at: 0,
+            duration_ms: 1_000,
+        };
+        ops.set_content_pricing(&hash, Some(rule)).unwrap();
+        let manifest = ops.state.manifests.load(&hash).unwrap().unwrap();
+        assert_eq!(manifest.economics.pricing, Some(rule));
+
+        // Raising the base price past the bounds is refused
+        assert!(ops.set_content_price(&hash, 6_000).is_err());
+
+        ops.set_content_pricing(&hash, None).unwrap();
+        let manifest = ops.state.manifests.load(&hash).unwrap().unwrap();
+        assert_eq!(manifest.economics.pricing, None);
+        assert_eq!(ops.current_price(&manifest, 0).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn test_check_remote_pricing() {
+        let (_, public_key) = generate_identity();
+        let hash = content_hash(b"remote");
+        let mut manifest = Manifest::new_l0(
+            hash,
+            peer_id_from_public_key(&public_key),
+            Metadata::new("Remote", 1_000),
+            current_timestamp(),
+        );
+        manifest.economics.price = 1_000;
+        assert!(check_remote_pricing(&manifest).is_ok());
+
+        manifest.economics.pricing = Some(PricingRule::Surge {
+            max_price: 2_000,
+            window_ms: 600_000,
+            step_bps: 1_000,
+        });
+        assert!(check_remote_pricing(&manifest).is_ok());
+
+        // A step no publish would accept
+        manifest.economics.pricing = Some(PricingRule::Surge {
+            max_price: 2_000,
+            window_ms: 600_000,
+            step_bps: u32::MAX,
+        });
+        assert!(matches!(
+            check_remote_pricing(&manifest),
+            Err(OpsError::Econ(EconError::InvalidPricingRule { .. }))
+        ));
+    }
+}
@@ -1,14 +1,20 @@
 //! Publish operations implementation.
 //!
 //! This module implements publish, unpublish, set_visibility, and set_access operations
-//! as specified in Protocol Specification §7.1.3.
+//! as specified in Protocol Specification §7.1.3, plus scheduled (embargoed)
+//! publishing.
 
-use nodalync_crypto::Hash;
-use nodalync_econ::validate_price;
+use nodalync_crypto::{Hash, Timestamp};
+use nodalync_econ::{validate_price, validate_pricing_rule};
 use nodalync_net::Multiaddr;
-use nodalync_store::ManifestStore;
-use nodalync_types::{AccessControl, Amount, ContentType, Manifest, Visibility};
-use nodalync_valid::Validator;
+use nodalync_store::{ContentStore, GroupStore, ManifestFilter, ManifestStore};
+use nodalync_types::{
+    normalize_tags, tag_path, AccessControl, Amount, ContentType, Manifest, PreviewPolicy,
+    PricingRule, ReplicaDelegation, Visibility, MAX_GROUPS_PER_CONTENT, MAX_TAGS,
+};
+use nodalync_valid::{
+    sign_announcement, validate_metadata, validate_quota_policy, validate_trial_policy, Validator,
+};
 use nodalync_wire::AnnouncePayload;
 
 use crate::error::{OpsError, OpsResult};
@@ -30,12 +36,117 @@ where
     /// 5. Announces to DHT (if network available)
     ///
     /// Note: L2 content cannot be published - it must remain private.
+    /// Collections can only be published at their bundle price, once all of
+    /// their items are published. Publishing lifts any scheduled embargo.
+    #[tracing::instrument(name = "publish", skip(self), fields(%hash))]
     pub async fn publish_content(
-        &mut self,
+        &self,
         hash: &Hash,
         visibility: Visibility,
         price: Amount,
     ) -> OpsResult<()> {
+        let (mut manifest, l1_summary) = self.prepare_publish(hash, visibility, price)?;
+        manifest.access.publish_at = None;
+        self.record_bond(&mut manifest).await;
+
+        // Save manifest
+        self.state.manifests.update(&manifest)?;
+        self.register_tags(&manifest.metadata.tags)?;
+
+        // Network announce (if network available)
+        self.announce_manifest(&manifest, l1_summary).await;
+
+        Ok(())
+    }
+
+    /// Schedule content to be published at `publish_at`.
+    ///
+    /// The manifest is updated now (visibility, price, tags) with
+    /// `access.publish_at` set, but nothing is announced. Until then, queries
+    /// and previews from other peers are rejected with
+    /// `ValidationError::Embargoed`. `publish_scheduled_content` announces the
+    /// content once the time has passed.
+    ///
+    /// A `publish_at` that is not in the future publishes immediately.
+    pub async fn schedule_publish(
+        &self,
+        hash: &Hash,
+        visibility: Visibility,
+        price: Amount,
+        publish_at: Timestamp,
+    ) -> OpsResult<()> {
+        if publish_at <= current_timestamp() {
+            return self.publish_content(hash, visibility, price).await;
+        }
+
+        let (mut manifest, _) = self.prepare_publish(hash, visibility, price)?;
+        manifest.access.publish_at = Some(publish_at);
+        self.record_bond(&mut manifest).await;
+        self.state.manifests.update(&manifest)?;
+        self.register_tags(&manifest.metadata.tags)?;
+
+        Ok(())
+    }
+
+    /// Announce scheduled content whose publish time has passed.
+    ///
+    /// Clears the embargo on each due manifest and announces it. Returns the
+    /// hashes that were published.
+    pub async fn publish_scheduled_content(&self, now: Timestamp) -> OpsResult<Vec<Hash>> {
+        let due: Vec<Manifest> = self
+            .scheduled_manifests()?
+            .into_iter()
+            .filter(|m| !m.access.is_embargoed(now))
+            .collect();
+
+        let mut published = Vec::with_capacity(due.len());
+        for mut manifest in due {
+            manifest.access.publish_at = None;
+            manifest.updated_at = now;
+            self.state.manifests.update(&manifest)?;
+
+            let l1_summary = self.preview_summary(&manifest.hash)?;
+            self.announce_manifest(&manifest, l1_summary).await;
+            published.push(manifest.hash);
+        }
+
+        Ok(published)
+    }
+
+    /// Get the earliest pending scheduled publish time, if any.
+    pub fn next_scheduled_publish(&self) -> OpsResult<Option<Timestamp>> {
+        Ok(self
+            .scheduled_manifests()?
+            .iter()
+            .filter_map(|m| m.access.publish_at)
+            .min())
+    }
+
+    /// Own published manifests that still carry a publish time.
+    fn scheduled_manifests(&self) -> OpsResult<Vec<Manifest>> {
+        let filter = ManifestFilter::new().with_owner(self.peer_id());
+        Ok(self
+            .state
+            .manifests
+            .list(filter)?
+            .into_iter()
+            .filter(|m| {
+                m.access.publish_at.is_some()
+                    && !matches!(m.visibility, Visibility::Private | Visibility::Offline)
+            })
+            .collect())
+    }
+
+    /// Load a manifest and apply the publish settings without saving it.
+    ///
+    /// Checks ownership, withdrawal and price rules, then updates visibility
+    /// and price, and adds the L1 extraction's topics to the author's tags.
+    fn prepare_publish(
+        &self,
+        hash: &Hash,
+        visibility: Visibility,
+        price: Amount,
+    ) -> OpsResult<(Manifest, nodalync_types::L1Summary)> {
         // 1. Load manifest
         let mut manifest = self
             .state
@@ -55,59 +166,105 @@ where
             return Err(OpsError::AccessDenied);
         }
 
+        // A withdrawal is final: peers refuse the hash from now on
+        self.ensure_not_withdrawn(&manifest)?;
+
         // 2. Validate price
         if price > 0 {
             validate_price(price)?;
         }
+        if manifest.content_type == ContentType::Collection {
+            self.check_collection_publish(&manifest, price)?;
+        }
 
-        // 3. Extract L1 summary to get topics
-        let l1_summary = self.extract_l1_summary(hash)?;
+        // 3. Extract the (redacted) preview summary to get topics
+        let l1_summary = self.preview_summary(hash)?;
 
-        // 4. Update visibility, price, and tags from L1 extraction
+        // 4. Update visibility and price, and add tags from L1 extraction
         manifest.visibility = visibility;
         manifest.economics.price = price;
-        manifest.metadata.tags = l1_summary.primary_topics.clone();
+        let mut tags = manifest.metadata.tags.clone();
+        tags.extend(l1_summary.primary_topics.iter().cloned());
+        manifest.metadata.tags = normalize_tags(&tags);
+        manifest.metadata.tags.truncate(MAX_TAGS);
         manifest.updated_at = current_timestamp();
 
-        // 5. Save manifest
-        self.state.manifests.update(&manifest)?;
+        Ok((manifest, l1_summary))
+    }
+
+    /// Announce a published manifest to the DHT and via GossipSub.
+    ///
+    /// Both are best-effort; failures are logged and the content stays
+    /// published locally.
+    async fn announce_manifest(&self, manifest: &Manifest, l1_summary: nodalync_types::L1Summary) {
+        self.announce_manifest_with(manifest, l1_summary, None)
+            .await;
+    }
+
+    /// Announce a manifest, attaching a replica delegation if we are
+    /// announcing a primary's content.
+    pub(crate) async fn announce_manifest_with(
+        &self,
+        manifest: &Manifest,
+        l1_summary: nodalync_types::L1Summary,
+        delegation: Option<ReplicaDelegation>,
+    ) {
+        let Some(network) = self.network().cloned() else {
+            return;
+        };
+        let hash = &manifest.hash;
+
+        // Include our libp2p peer ID so other nodes can dial us directly
+        let publisher_peer_id = Some(network.local_peer_id().to_string());
+        let listen_addrs = network.listen_addresses();
+        tracing::debug!(
+            "Publishing content: hash={}, publisher_peer_id={:?}, listen_addresses={:?}",
+            hash,
+            publisher_peer_id,
+            listen_addrs
+        );
+        let mut payload =
+            Self::create_announce_payload(manifest, l1_summary, listen_addrs, publisher_peer_id);
+        payload.delegation = delegation;
+
+        // Sign as the publisher (or its replica) so receivers can verify
+        // who announced it
+        if let Some(private_key) = self.private_key() {
+            if let Err(e) = sign_announcement(private_key, &mut payload) {
+                tracing::warn!("Failed to sign announcement: {}", e);
+            }
+        }
 
-        // 6. Network announce (if network available)
-        if let Some(network) = self.network().cloned() {
-            // Include our libp2p peer ID so other nodes can dial us directly
-            let publisher_peer_id = Some(network.local_peer_id().to_string());
-            let listen_addrs = network.listen_addresses();
-            tracing::debug!(
-                "Publishing content: hash={}, publisher_peer_id={:?}, listen_addresses={:?}",
-                hash,
-                publisher_peer_id,
-                listen_addrs
+        // DHT announce for persistence - best-effort
+        if let Err(e) = network.dht_announce(*hash, payload.clone()).await {
+            tracing::warn!(
+                "DHT announce failed (content still published locally): {}",
+                e
             );
-            let payload = Self::create_announce_payload(
-                &manifest,
-                l1_summary,
-                listen_addrs,
-                publisher_peer_id,
+        }
+
+        // GossipSub broadcast for immediate discovery - best-effort
+        if let Err(e) = network.broadcast_announce(payload.clone()).await {
+            tracing::warn!(
+                "GossipSub broadcast failed (content still published locally): {}",
+                e
             );
+        }
 
-            // DHT announce for persistence - best-effort
-            if let Err(e) = network.dht_announce(*hash, payload.clone()).await {
-                tracing::warn!(
-                    "DHT announce failed (content still published locally): {}",
-                    e
-                );
+        // Also announce on each tag's topic and its ancestors' - best-effort
+        let mut topics: Vec<&str> = Vec::new();
+        for tag in &manifest.metadata.tags {
+            for path_tag in tag_path(tag) {
+                if !topics.contains(&path_tag) {
+                    topics.push(path_tag);
+                }
             }
-
-            // GossipSub broadcast for immediate discovery - best-effort
-            if let Err(e) = network.broadcast_announce(payload).await {
-                tracing::warn!(
-                    "GossipSub broadcast failed (content still published locally): {}",
-                    e
-                );
+        }
+        for tag in topics {
+            if let Err(e) = network.broadcast_tag_announce(tag, payload.clone()).await {
+                tracing::debug!(tag, "Tag announce failed: {}", e);
             }
         }
-
-        Ok(())
     }
 
     /// Create an AnnouncePayload from a manifest.
@@ -128,15 +285,22 @@ where
                 .map(|addr: &Multiaddr| addr.to_string())
                 .collect(),
             publisher_peer_id,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
         }
     }
 
     /// Unpublish content from the network.
     ///
     /// Spec §7.1.3:
-    /// - Sets visibility to Private
+    /// - Sets visibility to Private and clears any scheduled publish
+    /// - Signs and broadcasts a tombstone so peers drop their copies
     /// - Removes from DHT (if network available)
-    pub async fn unpublish_content(&mut self, hash: &Hash) -> OpsResult<()> {
+    ///
+    /// The tombstone makes the withdrawal final: the content can't be
+    /// published again. Without a private key it is skipped with a warning.
+    pub async fn unpublish_content(&self, hash: &Hash) -> OpsResult<()> {
         // Load manifest
         let mut manifest = self
             .state
@@ -149,13 +313,57 @@ where
             return Err(OpsError::AccessDenied);
         }
 
-        // Set visibility to Private
+        // Set visibility to Private (cancelling any scheduled publish)
         manifest.visibility = Visibility::Private;
+        manifest.access.publish_at = None;
         manifest.updated_at = current_timestamp();
 
         // Save manifest
         self.state.manifests.update(&manifest)?;
 
+        self.withdraw_and_remove(hash).await;
+
+        Ok(())
+    }
+
+    /// Delete content from this node.
+    ///
+    /// Removes the content bytes and takes the manifest Offline (it is kept
+    /// for provenance), then withdraws the content from the network like
+    /// [`Self::unpublish_content`].
+    pub async fn delete_content(&self, hash: &Hash) -> OpsResult<()> {
+        let mut manifest = self
+            .state
+            .manifests
+            .load(hash)?
+            .ok_or(OpsError::ManifestNotFound(*hash))?;
+
+        if manifest.owner != self.peer_id() {
+            return Err(OpsError::AccessDenied);
+        }
+
+        self.state.content.delete(hash)?;
+        manifest.visibility = Visibility::Offline;
+        manifest.access.publish_at = None;
+        manifest.updated_at = current_timestamp();
+        self.state.manifests.update(&manifest)?;
+
+        self.withdraw_and_remove(hash).await;
+
+        Ok(())
+    }
+
+    /// Broadcast a tombstone for our content and remove it from the DHT.
+    ///
+    /// Both are best-effort; the content is already withdrawn locally.
+    async fn withdraw_and_remove(&self, hash: &Hash) {
+        if let Err(e) = self.withdraw_content(hash).await {
+            tracing::warn!(
+                "Tombstone not issued (content still withdrawn locally): {}",
+                e
+            );
+        }
+
         // DHT remove (if network available) - best-effort
         if let Some(network) = self.network() {
             if let Err(e) = network.dht_remove(hash).await {
@@ -165,12 +373,10 @@ where
                 );
             }
         }
-
-        Ok(())
     }
 
     /// Set visibility level for content.
-    pub fn set_content_visibility(&mut self, hash: &Hash, visibility: Visibility) -> OpsResult<()> {
+    pub fn set_content_visibility(&self, hash: &Hash, visibility: Visibility) -> OpsResult<()> {
         // Load manifest
         let mut manifest = self
             .state
@@ -194,7 +400,7 @@ where
     }
 
     /// Set access control for content.
-    pub fn set_content_access(&mut self, hash: &Hash, access: AccessControl) -> OpsResult<()> {
+    pub fn set_content_access(&self, hash: &Hash, access: AccessControl) -> OpsResult<()> {
         // Load manifest
         let mut manifest = self
             .state
@@ -207,6 +413,28 @@ where
             return Err(OpsError::AccessDenied);
         }
 
+        if let Some(trial) = &access.trial {
+            validate_trial_policy(trial)?;
+        }
+        if let Some(quota) = &access.quota {
+            validate_quota_policy(quota)?;
+        }
+
+        // Referenced groups must be known locally, so they can be resolved
+        if let Some(groups) = &access.groups {
+            if groups.len() > MAX_GROUPS_PER_CONTENT {
+                return Err(OpsError::invalid_operation(format!(
+                    "at most {} groups per content",
+                    MAX_GROUPS_PER_CONTENT
+                )));
+            }
+            for id in groups {
+                if self.state.groups.get(id)?.is_none() {
+                    return Err(OpsError::GroupNotFound(*id));
+                }
+            }
+        }
+
         // Update access control
         manifest.access = access;
         manifest.updated_at = current_timestamp();
@@ -217,8 +445,41 @@ where
         Ok(())
     }
 
+    /// Set the preview policy for content.
+    ///
+    /// Applies to previews and search results straight away. Announcements
+    /// peers already hold keep the old summary until the content is
+    /// published again. An empty policy clears it.
+    pub fn set_content_preview_policy(
+        &self,
+        hash: &Hash,
+        policy: Option<PreviewPolicy>,
+    ) -> OpsResult<()> {
+        // Load manifest
+        let mut manifest = self
+            .state
+            .manifests
+            .load(hash)?
+            .ok_or(OpsError::ManifestNotFound(*hash))?;
+
+        // Verify ownership
+        if manifest.owner != self.peer_id() {
+            return Err(OpsError::AccessDenied);
+        }
+
+        // Update and validate the policy
+        manifest.metadata.preview_policy = policy.filter(|p| !p.is_empty());
+        validate_metadata(&manifest)?;
+        manifest.updated_at = current_timestamp();
+
+        // Save manifest
+        self.state.manifests.update(&manifest)?;
+
+        Ok(())
+    }
+
     /// Set price for content.
-    pub fn set_content_price(&mut self, hash: &Hash, price: Amount) -> OpsResult<()> {
+    pub fn set_content_price(&self, hash: &Hash, price: Amount) -> OpsResult<()> {
         // Validate price
         if price > 0 {
             validate_price(price)?;
@@ -236,8 +497,67 @@ where
             return Err(OpsError::AccessDenied);
         }
 
-        // Update price
+        // A collection's price is fixed by its content
+        if manifest.content_type == ContentType::Collection {
+            let expected = self.get_collection(hash)?.price();
+            if price != expected {
+                return Err(OpsError::Validation(
+                    nodalync_valid::ValidationError::CollectionPriceMismatch {
+                        expected,
+                        actual: price,
+                    },
+                ));
+            }
+        }
+
+        // A pricing rule's bounds must stay above the new base price
+        if let Some(rule) = &manifest.economics.pricing {
+            validate_pricing_rule(price, rule)?;
+        }
+
+        // Update price; metered units must each still cost something
         manifest.economics.price = price;
+        if manifest.metadata.metering.is_some() {
+            validate_metadata(&manifest)?;
+        }
+        manifest.updated_at = current_timestamp();
+
+        // Save manifest
+        self.state.manifests.update(&manifest)?;
+
+        Ok(())
+    }
+
+    /// Set the dynamic pricing rule for content.
+    ///
+    /// The content's price stays the floor the rule moves up from; `None`
+    /// goes back to charging it flat. Peers see the rule in previews, so
+    /// they can check what they are charged.
+    pub fn set_content_pricing(&self, hash: &Hash, pricing: Option<PricingRule>) -> OpsResult<()> {
+        // Load manifest
+        let mut manifest = self
+            .state
+            .manifests
+            .load(hash)?
+            .ok_or(OpsError::ManifestNotFound(*hash))?;
+
+        // Verify ownership
+        if manifest.owner != self.peer_id() {
+            return Err(OpsError::AccessDenied);
+        }
+
+        if let Some(rule) = &pricing {
+            // A collection's price is fixed by its content
+            if manifest.content_type == ContentType::Collection {
+                return Err(OpsError::invalid_operation(
+                    "collections can't use dynamic pricing",
+                ));
+            }
+            validate_pricing_rule(manifest.economics.price, rule)?;
+        }
+
+        // Update pricing
+        manifest.economics.pricing = pricing;
         manifest.updated_at = current_timestamp();
 
         // Save manifest
@@ -270,7 +590,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_publish_content() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         let content = b"Content to publish";
         let meta = Metadata::new("Publish Test", content.len() as u64);
@@ -293,7 +613,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_unpublish_content() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         let content = b"Content to unpublish";
         let meta = Metadata::new("Unpublish Test", content.len() as u64);
@@ -314,7 +634,7 @@ mod tests {
 
     #[test]
     fn test_set_visibility() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         let content = b"Content for visibility test";
         let meta = Metadata::new("Visibility Test", content.len() as u64);
@@ -337,7 +657,7 @@ mod tests {
 
     #[test]
     fn test_set_access_control() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         let content = b"Content for access test";
         let meta = Metadata::new("Access Test", content.len() as u64);
@@ -357,7 +677,7 @@ mod tests {
 
     #[test]
     fn test_set_price() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         let content = b"Content for price test";
         let meta = Metadata::new("Price Test", content.len() as u64);
@@ -372,7 +692,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_publish_invalid_price() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         let content = b"Content with invalid price";
         let meta = Metadata::new("Invalid Price", content.len() as u64);
@@ -389,7 +709,7 @@ mod tests {
     /// should remain Private and price should stay at 0.
     #[tokio::test]
     async fn test_publish_extreme_price_no_ghost_content() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         let content = b"Content that should not become ghost";
         let meta = Metadata::new("Ghost Test", content.len() as u64);
@@ -422,7 +742,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_publish_requires_ownership() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create content
         let content = b"Owned content";
@@ -439,4 +759,84 @@ mod tests {
         let result = ops.publish_content(&hash, Visibility::Shared, 100).await;
         assert!(matches!(result, Err(OpsError::AccessDenied)));
     }
+
+    #[tokio::test]
+    async fn test_schedule_publish() {
+        let (ops, _temp) = create_test_ops();
+
+        let content = b"Embargoed content";
+        let meta = Metadata::new("Embargo Test", content.len() as u64);
+        let hash = ops.create_content(content, meta).unwrap();
+
+        let publish_at = current_timestamp() + 3_600_000;
+        ops.schedule_publish(&hash, Visibility::Shared, 100, publish_at)
+            .await
+            .unwrap();
+
+        let manifest = ops.state.manifests.load(&hash).unwrap().unwrap();
+        assert_eq!(manifest.visibility, Visibility::Shared);
+        assert_eq!(manifest.economics.price, 100);
+        assert_eq!(manifest.access.publish_at, Some(publish_at));
+        assert_eq!(ops.next_scheduled_publish().unwrap(), Some(publish_at));
+
+        // Early previews are rejected
+        let requester = {
+            let (_, pk) = generate_identity();
+            peer_id_from_public_key(&pk)
+        };
+        let request = nodalync_wire::PreviewRequestPayload { hash };
+        let result = ops.handle_preview_request(&requester, &request);
+        assert!(matches!(
+            result,
+            Err(OpsError::Validation(
+                nodalync_valid::ValidationError::Embargoed { .. }
+            ))
+        ));
+
+        // Nothing is due before the publish time
+        let published = ops.publish_scheduled_content(publish_at - 1).await.unwrap();
+        assert!(published.is_empty());
+
+        let published = ops.publish_scheduled_content(publish_at).await.unwrap();
+        assert_eq!(published, vec![hash]);
+        let manifest = ops.state.manifests.load(&hash).unwrap().unwrap();
+        assert_eq!(manifest.access.publish_at, None);
+        assert_eq!(ops.next_scheduled_publish().unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_schedule_publish_in_past_publishes_now() {
+        let (ops, _temp) = create_test_ops();
+
+        let content = b"Late content";
+        let meta = Metadata::new("Late", content.len() as u64);
+        let hash = ops.create_content(content, meta).unwrap();
+
+        ops.schedule_publish(&hash, Visibility::Shared, 0, 1)
+            .await
+            .unwrap();
+
+        let manifest = ops.state.manifests.load(&hash).unwrap().unwrap();
+        assert_eq!(manifest.visibility, Visibility::Shared);
+        assert_eq!(manifest.access.publish_at, None);
+    }
+
+    #[tokio::test]
+    async fn test_unpublish_cancels_schedule() {
+        let (ops, _temp) = create_test_ops();
+
+        let content = b"Cancelled content";
+        let meta = Metadata::new("Cancelled", content.len() as u64);
+        let hash = ops.create_content(content, meta).unwrap();
+
+        let publish_at = current_timestamp() + 3_600_000;
+        ops.schedule_publish(&hash, Visibility::Shared, 0, publish_at)
+            .await
+            .unwrap();
+        ops.unpublish_content(&hash).await.unwrap();
+
+        let manifest = ops.state.manifests.load(&hash).unwrap().unwrap();
+        assert_eq!(manifest.access.publish_at, None);
+        assert_eq!(ops.next_scheduled_publish().unwrap(), None);
+    }
 }
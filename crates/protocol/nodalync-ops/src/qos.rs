@@ -0,0 +1,331 @@
+//! Peer latency measurement and QoS-ranked provider selection.
+//!
+//! [`probe_peer_latency`](NodeOperations::probe_peer_latency) pings known,
+//! connected peers and records each round trip, or the lost ping, in the
+//! peer store, which keeps latency, jitter and loss over the last
+//! [`LATENCY_WINDOW`](nodalync_store::LATENCY_WINDOW) probes. Peers that
+//! haven't been probed for longest go first, so a probe capped at
+//! `max_peers` still covers everyone over time.
+//!
+//! When content can be fetched from several providers, they are tried in
+//! order of a blend of reputation and QoS score
+//! ([`QosConfig::weight`](crate::QosConfig::weight)). Peers without a
+//! record or without samples count as average on either measure.
+
+use std::cmp::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::stream::{self, StreamExt};
+use nodalync_crypto::PeerId;
+use nodalync_net::Network;
+use nodalync_store::{PeerInfo, PeerStore, StoreError};
+use nodalync_valid::Validator;
+use nodalync_wire::PingPayload;
+use tracing::debug;
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+/// Reputation at or beyond which a peer counts as fully (dis)reputable
+/// when ranking providers.
+const REPUTATION_RANGE: i64 = 100;
+
+/// Score given to a peer on a measure it has no record of.
+const NEUTRAL_SCORE: f64 = 0.5;
+
+/// Outcome of one latency probe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyProbe {
+    /// Number of peers pinged.
+    pub pinged: usize,
+    /// Round trip to each peer pinged, in milliseconds, or `None` if the
+    /// ping was lost.
+    pub samples: Vec<(PeerId, Option<u32>)>,
+}
+
+impl LatencyProbe {
+    /// Number of peers that answered.
+    pub fn answered(&self) -> usize {
+        self.samples.iter().filter(|(_, rtt)| rtt.is_some()).count()
+    }
+}
+
+/// A peer's reputation mapped onto 0.0..=1.0, 0 reputation being 0.5.
+fn reputation_score(reputation: i64) -> f64 {
+    let reputation = reputation.clamp(-REPUTATION_RANGE, REPUTATION_RANGE);
+    (reputation + REPUTATION_RANGE) as f64 / (2 * REPUTATION_RANGE) as f64
+}
+
+/// Rank of a provider: `weight` of its QoS score and the rest of its
+/// reputation score.
+fn provider_score(info: Option<&PeerInfo>, weight: f64) -> f64 {
+    let reputation = info.map_or(NEUTRAL_SCORE, |info| reputation_score(info.reputation));
+    let qos = info.and_then(PeerInfo::qos_score).unwrap_or(NEUTRAL_SCORE);
+    (1.0 - weight) * reputation + weight * qos
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Ping up to `max_peers` known, connected peers and record their
+    /// round trips in the peer store.
+    ///
+    /// Connected peers we hold no record of are skipped; there's nowhere
+    /// to keep their statistics.
+    pub async fn probe_peer_latency(&self) -> OpsResult<LatencyProbe> {
+        let network = self
+            .network()
+            .cloned()
+            .ok_or_else(|| OpsError::invalid_operation("network required to probe peer latency"))?;
+        let config = self.config.qos.clone();
+
+        let mut peers: Vec<_> = network
+            .connected_peers()
+            .into_iter()
+            .filter_map(|libp2p_peer| {
+                let peer = network.nodalync_peer_id(&libp2p_peer)?;
+                let info = self.state.peers.get(&peer).ok().flatten()?;
+                let last_probe = info.latency.map(|stats| stats.last_probe);
+                Some((libp2p_peer, peer, last_probe))
+            })
+            .collect();
+        // Never probed first, then least recently probed
+        peers.sort_by_key(|(_, _, last_probe)| *last_probe);
+        peers.truncate(config.max_peers);
+        let pinged = peers.len();
+
+        let timeout = Duration::from_millis(config.ping_timeout_ms);
+        let results: Vec<_> = stream::iter(peers)
+            .map(|(libp2p_peer, peer, _)| {
+                let network = network.clone();
+                async move {
+                    let nonce = rand::random();
+                    let sent = Instant::now();
+                    let pong = tokio::time::timeout(
+                        timeout,
+                        network.send_ping(libp2p_peer, PingPayload { nonce }),
+                    )
+                    .await;
+                    let rtt = match pong {
+                        Ok(Ok(pong)) if pong.nonce == nonce => {
+                            Some(sent.elapsed().as_millis().min(u32::MAX as u128) as u32)
+                        }
+                        Ok(Ok(_)) => None,
+                        Ok(Err(e)) => {
+                            debug!(peer = %libp2p_peer, error = %e, "Latency ping failed");
+                            None
+                        }
+                        Err(_) => {
+                            debug!(peer = %libp2p_peer, "Latency ping timed out");
+                            None
+                        }
+                    };
+                    (peer, rtt)
+                }
+            })
+            .buffer_unordered(pinged.max(1))
+            .collect()
+            .await;
+
+        let timestamp = current_timestamp();
+        let mut samples = Vec::with_capacity(results.len());
+        for (peer, rtt) in results {
+            match self.state.peers.record_latency(&peer, rtt, timestamp) {
+                Ok(_) => samples.push((peer, rtt)),
+                // Removed while we were waiting for its pong
+                Err(StoreError::PeerNotFound) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        debug!(
+            pinged,
+            answered = samples.iter().filter(|(_, rtt)| rtt.is_some()).count(),
+            "Peer latency probed"
+        );
+
+        Ok(LatencyProbe { pinged, samples })
+    }
+
+    /// Order providers best first by reputation and QoS score. The sort is
+    /// stable, so equally ranked providers keep their order.
+    pub(crate) fn rank_providers(
+        &self,
+        network: &Arc<dyn Network>,
+        providers: &mut [nodalync_net::PeerId],
+    ) {
+        if providers.len() < 2 {
+            return;
+        }
+        let weight = self.config.qos.weight;
+        let mut scored: Vec<_> = providers
+            .iter()
+            .map(|provider| {
+                let info = network
+                    .nodalync_peer_id(provider)
+                    .and_then(|peer| self.state.peers.get(&peer).ok().flatten());
+                (*provider, provider_score(info.as_ref(), weight))
+            })
+            .collect();
+        scored.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+        for (slot, (provider, _)) in providers.iter_mut().zip(scored) {
+            *slot = provider;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{OpsConfig, QosConfig};
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_test_utils::MockNetwork;
+    use tempfile::TempDir;
+
+    fn create_test_ops(qos: QosConfig) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (_, public_key) = generate_identity();
+        let config = OpsConfig::default().with_qos(qos);
+        let ops =
+            DefaultNodeOperations::with_config(state, peer_id_from_public_key(&public_key), config);
+        (ops, temp_dir)
+    }
+
+    /// Add a connected peer to the network and, with a reputation, to the
+    /// peer store. Peers with a clock offset answer pings.
+    fn add_peer(
+        ops: &DefaultNodeOperations,
+        network: MockNetwork,
+        reputation: Option<i64>,
+        answers: bool,
+    ) -> (MockNetwork, nodalync_net::PeerId, PeerId) {
+        let libp2p_peer = nodalync_net::PeerId::random();
+        let (_, public_key) = generate_identity();
+        let peer = peer_id_from_public_key(&public_key);
+        if let Some(reputation) = reputation {
+            let mut info = PeerInfo::new(peer, public_key, vec![], current_timestamp());
+            info.reputation = reputation;
+            ops.state.peers.upsert(&info).unwrap();
+        }
+        let mut network = network
+            .with_connected_peer(libp2p_peer)
+            .with_peer_mapping(libp2p_peer, peer);
+        if answers {
+            network = network.with_peer_clock_offset(libp2p_peer, 0);
+        }
+        (network, libp2p_peer, peer)
+    }
+
+    #[test]
+    fn test_provider_score() {
+        assert_eq!(reputation_score(0), 0.5);
+        assert_eq!(reputation_score(-500), 0.0);
+        assert_eq!(reputation_score(100), 1.0);
+        assert_eq!(provider_score(None, 0.5), NEUTRAL_SCORE);
+
+        let (_, public_key) = generate_identity();
+        let mut info = PeerInfo::new(peer_id_from_public_key(&public_key), public_key, vec![], 0);
+        info.reputation = 100;
+        // Unmeasured: average QoS
+        assert_eq!(provider_score(Some(&info), 0.5), 0.75);
+        assert_eq!(provider_score(Some(&info), 0.0), 1.0);
+
+        let mut latency = nodalync_store::LatencyStats::default();
+        latency.record(None, 1);
+        info.latency = Some(latency);
+        // Every ping lost
+        assert_eq!(provider_score(Some(&info), 0.5), 0.5);
+        assert_eq!(provider_score(Some(&info), 1.0), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_probe_peer_latency() {
+        let (mut ops, _temp) = create_test_ops(QosConfig::default());
+        let network = MockNetwork::new();
+        let (network, _, fast) = add_peer(&ops, network, Some(0), true);
+        let (network, _, silent) = add_peer(&ops, network, Some(0), false);
+        // Connected but unknown to the peer store: skipped
+        let (network, _, unknown) = add_peer(&ops, network, None, true);
+        ops.set_network(Arc::new(network));
+
+        let probe = ops.probe_peer_latency().await.unwrap();
+        assert_eq!(probe.pinged, 2);
+        assert_eq!(probe.answered(), 1);
+        assert!(probe.samples.iter().all(|(peer, _)| *peer != unknown));
+
+        let stats = ops
+            .state
+            .peers
+            .get(&fast)
+            .unwrap()
+            .unwrap()
+            .latency
+            .unwrap();
+        assert_eq!((stats.probes, stats.lost), (1, 0));
+        assert!(stats.percentile(50.0).is_some());
+        let stats = ops
+            .state
+            .peers
+            .get(&silent)
+            .unwrap()
+            .unwrap()
+            .latency
+            .unwrap();
+        assert_eq!((stats.probes, stats.lost), (1, 1));
+        assert_eq!(stats.qos_score(), Some(0.0));
+        assert!(ops.state.peers.get(&unknown).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_probe_rotates_through_peers() {
+        let (mut ops, _temp) = create_test_ops(QosConfig::default().with_max_peers(1));
+        let (network, _, first) = add_peer(&ops, MockNetwork::new(), Some(0), true);
+        let (network, _, second) = add_peer(&ops, network, Some(0), true);
+        ops.set_network(Arc::new(network));
+
+        let mut probed = Vec::new();
+        for _ in 0..2 {
+            let probe = ops.probe_peer_latency().await.unwrap();
+            assert_eq!(probe.pinged, 1);
+            probed.push(probe.samples[0].0);
+        }
+        // The second probe picks the peer the first one left out
+        assert!(probed.contains(&first) && probed.contains(&second));
+    }
+
+    #[tokio::test]
+    async fn test_rank_providers() {
+        let (mut ops, _temp) = create_test_ops(QosConfig::default());
+        let (network, lossy, lossy_id) = add_peer(&ops, MockNetwork::new(), Some(50), false);
+        let (network, responsive, _) = add_peer(&ops, network, Some(0), true);
+        let (network, stranger, _) = add_peer(&ops, network, None, true);
+        let network: Arc<dyn Network> = Arc::new(network);
+        ops.set_network(network.clone());
+
+        // Before measuring, reputation decides; unknown peers count as 0
+        let mut providers = vec![stranger, responsive, lossy];
+        ops.rank_providers(&network, &mut providers);
+        assert_eq!(providers, vec![lossy, stranger, responsive]);
+
+        // Losing every ping outweighs the reputation lead
+        ops.probe_peer_latency().await.unwrap();
+        assert_eq!(
+            ops.state.peers.get(&lossy_id).unwrap().unwrap().qos_score(),
+            Some(0.0)
+        );
+        ops.rank_providers(&network, &mut providers);
+        assert_eq!(providers[0], responsive);
+        assert_eq!(providers[2], lossy);
+
+        // With no QoS weight, reputation alone decides again
+        ops.config.qos = QosConfig::default().with_weight(0.0);
+        ops.rank_providers(&network, &mut providers);
+        assert_eq!(providers, vec![lossy, responsive, stranger]);
+    }
+}
@@ -1,26 +1,35 @@
 //! Query operations implementation.
 //!
 //! This module implements preview, query, get_versions, and extract_l1 operations
-//! as specified in Protocol Specification §7.2 and §7.4.
+//! as specified in Protocol Specification §7.2 and §7.4, plus fetching newer
+//! versions as deltas.
 
 use nodalync_crypto::{content_hash, Hash, PeerId, Signature, UNKNOWN_PEER_ID};
+use nodalync_store::delta::apply_delta;
 use nodalync_store::{
     CacheStore, CachedContent, ChannelStore, ContentStore, ManifestFilter, ManifestStore,
+    PopularityKind,
 };
 use nodalync_types::{
-    Amount, ContentType, L1Summary, Manifest, Payment, ProvenanceEntry, Visibility,
+    Amount, CapabilityToken, ContentType, L1Summary, Manifest, Payment, ProvenanceEntry, Visibility,
 };
 use nodalync_valid::Validator;
 use nodalync_wire::{
-    PaymentReceipt, QueryRequestPayload, SearchFilters, SearchPayload, VersionInfo, VersionSpec,
+    Capability, PaymentReceipt, PreviewRequestPayload, QueryRequestPayload, SearchFilters,
+    SearchPayload, VersionInfo, VersionRequestPayload, VersionSpec,
 };
 
-use crate::channel::create_signed_payment;
+use tracing::{debug, info_span, Instrument};
+
 use crate::error::{OpsError, OpsResult};
 use crate::extraction::L1Extractor;
 use crate::helpers::verify_content_hash;
 use crate::node_ops::{current_timestamp, NodeOperations};
 use crate::ops::{PreviewResponse, QueryResponse};
+use crate::pricing::check_remote_pricing;
+use crate::referral::current_referrer;
+use crate::retry::names_alternatives;
+use crate::trace::{request_id_or_new, with_request_id};
 
 impl<V, E> NodeOperations<V, E>
 where
@@ -34,7 +43,7 @@ where
     /// 2. Uses configured extractor
     /// 3. Generates L1Summary
     /// 4. Stores L1 data (in-memory for MVP)
-    pub fn extract_l1_summary(&mut self, hash: &Hash) -> OpsResult<L1Summary> {
+    pub fn extract_l1_summary(&self, hash: &Hash) -> OpsResult<L1Summary> {
         // 1. Load content and manifest
         let manifest = self
             .state
@@ -53,13 +62,27 @@ where
         let mentions = self.extractor.extract(&content, mime_type)?;
 
         // 3. Generate L1Summary
+        let l1_summary = self.summarize_mentions(hash, &mentions);
+
+        // 4. For MVP, we don't persist L1 data separately
+        // Future: Store in a dedicated L1 store
+
+        Ok(l1_summary)
+    }
+
+    /// Build an L1Summary from extracted mentions.
+    pub(crate) fn summarize_mentions(
+        &self,
+        hash: &Hash,
+        mentions: &[nodalync_types::Mention],
+    ) -> L1Summary {
         let preview_mentions: Vec<_> = mentions
             .iter()
             .take(self.config.max_preview_mentions)
             .cloned()
             .collect();
 
-        let primary_topics: Vec<String> = extract_topics(&mentions);
+        let primary_topics: Vec<String> = extract_topics(mentions);
 
         let summary_text = if !mentions.is_empty() {
             format!(
@@ -71,18 +94,13 @@ where
             "No structured mentions extracted from this content.".to_string()
         };
 
-        let l1_summary = L1Summary::new(
+        L1Summary::new(
             *hash,
             mentions.len() as u32,
             preview_mentions,
             primary_topics,
             summary_text,
-        );
-
-        // 4. For MVP, we don't persist L1 data separately
-        // Future: Store in a dedicated L1 store
-
-        Ok(l1_summary)
+        )
     }
 
     /// Preview content metadata and L1 summary.
@@ -94,7 +112,7 @@ where
     /// 4. Checks access control
     /// 5. Gets or extracts L1Summary
     /// 6. Returns (Manifest, L1Summary)
-    pub async fn preview_content(&mut self, hash: &Hash) -> OpsResult<PreviewResponse> {
+    pub async fn preview_content(&self, hash: &Hash) -> OpsResult<PreviewResponse> {
         // 1. Try to load local manifest first
         if let Some(manifest) = self.state.manifests.load(hash)? {
             // 2-3. Check visibility and access
@@ -109,6 +127,11 @@ where
 
             // 4. Get or extract L1Summary
             let l1_summary = self.extract_l1_summary(hash)?;
+            let collection = if manifest.content_type == ContentType::Collection {
+                self.get_collection(hash).ok()
+            } else {
+                None
+            };
 
             // 5. Return response
             // If manifest.owner is UNKNOWN_PEER_ID, this is cached content from a network query
@@ -121,10 +144,19 @@ where
                 None // We own this content, no remote provider needed
             };
 
+            let forks = self.version_forks(&manifest)?;
+            let window_queries = if manifest.owner == self.peer_id() {
+                self.window_queries(&manifest, current_timestamp())?
+            } else {
+                0
+            };
             return Ok(PreviewResponse {
                 manifest,
                 l1_summary,
                 provider_peer_id,
+                collection,
+                forks,
+                window_queries,
             });
         }
 
@@ -143,9 +175,10 @@ where
         // If not in local announcements, try DHT lookup
         if let Some(network) = self.network().cloned() {
             if let Ok(Some(announcement)) = network.dht_get(hash).await {
-                // Store the announcement for future lookups
-                self.state.store_announcement(announcement.clone());
-                return Ok(Self::announcement_to_preview(announcement));
+                // Store the announcement for future lookups, unless it's forged
+                if self.store_verified_announcement(announcement.clone()) {
+                    return Ok(Self::announcement_to_preview(announcement));
+                }
             }
         }
 
@@ -169,6 +202,9 @@ where
                 currency: Currency::HBAR,
                 total_queries: 0,
                 total_revenue: 0,
+                bond: None,
+                pricing: None,
+                referral: None,
             },
             provenance: Provenance::new_l0(announcement.hash, UNKNOWN_PEER_ID),
             created_at: 0,
@@ -181,6 +217,9 @@ where
             // Preserve the publisher peer ID from the announcement
             // This is the libp2p peer ID of the node that can serve the content
             provider_peer_id: announcement.publisher_peer_id,
+            collection: None,
+            forks: Vec::new(),
+            window_queries: 0,
         }
     }
 
@@ -192,8 +231,60 @@ where
     /// 3. Validates payment amount >= price
     /// 4. Verifies response hash
     /// 5. Caches content
+    /// 6. Counts the query towards the content's popularity
+    /// 7. Records it in our library if paid for (see [`crate::library`])
+    ///
+    /// With a relay route in the network's `RelayConfig`, content we don't
+    /// hold is queried through the relays instead (see [`crate::relay`]).
+    ///
+    /// Runs in a `query` span whose request ID is sent with the query (see
+    /// [`crate::trace`]).
     pub async fn query_content(
-        &mut self,
+        &self,
+        hash: &Hash,
+        payment_amount: Amount,
+        version: Option<VersionSpec>,
+    ) -> OpsResult<QueryResponse> {
+        let request_id = request_id_or_new();
+        let span = info_span!("query", %request_id, %hash, amount = payment_amount);
+        with_request_id(request_id, async {
+            let response = self.fetch_content(hash, payment_amount, version).await?;
+            self.record_popularity(hash, PopularityKind::Query);
+            self.record_purchase(&response);
+            Ok(response)
+        })
+        .instrument(span)
+        .await
+    }
+
+    /// Query content, through relays if configured, without counting the
+    /// query towards its popularity.
+    pub(crate) async fn fetch_content(
+        &self,
+        hash: &Hash,
+        payment_amount: Amount,
+        version: Option<VersionSpec>,
+    ) -> OpsResult<QueryResponse> {
+        if let Some(relay) = self.network().map(|n| n.relay_config()) {
+            if !relay.route.is_empty() && !self.state.content.exists(hash) {
+                return self.query_via_relays(hash, payment_amount, &relay).await;
+            }
+        }
+        let result = self
+            .query_content_direct(hash, payment_amount, version)
+            .await;
+        match self.network().cloned() {
+            Some(network) => {
+                self.query_alternatives(hash, payment_amount, &network, result)
+                    .await
+            }
+            None => result,
+        }
+    }
+
+    /// Query content without going through relays.
+    pub(crate) async fn query_content_direct(
+        &self,
         hash: &Hash,
         payment_amount: Amount,
         _version: Option<VersionSpec>,
@@ -219,12 +310,17 @@ where
                         timestamp,
                         channel_nonce: 0,
                         distributor_signature: Signature::from_bytes([0u8; 64]),
+                        app_fee: 0,
+                        app_fee_recipient: None,
+                        referral_fee: 0,
+                        referrer: None,
                     };
 
                     return Ok(QueryResponse {
                         content,
                         manifest: manifest.clone(),
                         receipt,
+                        bundle: Vec::new(),
                     });
                 }
 
@@ -243,6 +339,10 @@ where
                         timestamp,
                         channel_nonce: 1,
                         distributor_signature: Signature::from_bytes([0u8; 64]),
+                        app_fee: 0,
+                        app_fee_recipient: None,
+                        referral_fee: 0,
+                        referrer: None,
                     };
 
                     // Cache content
@@ -259,11 +359,21 @@ where
                         content,
                         manifest: manifest.clone(),
                         receipt,
+                        bundle: Vec::new(),
                     });
                 }
 
                 // Content manifest exists but content not local - try network
                 if let Some(network) = self.network().cloned() {
+                    // Large free content comes chunk by chunk from every
+                    // provider, falling back to a whole-content query
+                    if manifest.economics.price == 0 && manifest.metadata.chunks.is_some() {
+                        match self.download_chunked(hash).await {
+                            Ok(response) => return Ok(response),
+                            Err(e) => debug!(hash = %hash, error = %e, "Chunked download failed"),
+                        }
+                    }
+
                     // If owner is unknown (all zeros), the preview came from a DHT announcement
                     // In that case, look up the announcement and use fetch_content_from_dht_announce
                     if manifest.owner == UNKNOWN_PEER_ID {
@@ -293,7 +403,13 @@ where
 
                     // Known owner - try direct network fetch
                     return self
-                        .fetch_content_from_network(hash, &manifest.owner, payment_amount, &network)
+                        .fetch_content_from_network(
+                            hash,
+                            &manifest.owner,
+                            payment_amount,
+                            &network,
+                            None,
+                        )
                         .await;
                 }
 
@@ -326,12 +442,13 @@ where
     }
 
     /// Fetch content from a known peer via the network.
-    async fn fetch_content_from_network(
-        &mut self,
+    pub(crate) async fn fetch_content_from_network(
+        &self,
         hash: &Hash,
         owner: &PeerId,
         payment_amount: Amount,
         network: &std::sync::Arc<dyn nodalync_net::Network>,
+        capability: Option<CapabilityToken>,
     ) -> OpsResult<QueryResponse> {
         let timestamp = current_timestamp();
 
@@ -349,11 +466,12 @@ where
                 .get(owner)?
                 .ok_or(OpsError::ChannelRequired)?;
 
-            // Require private key for signing
-            let private_key = self.private_key().ok_or(OpsError::PrivateKeyRequired)?;
-
-            // Get provenance from manifest or announcement
+            // Get provenance from manifest or announcement. Content shared
+            // by capability is unannounced, so it is taken to be its own root.
             let provenance = if let Some(manifest) = self.state.manifests.load(hash)? {
+                self.ensure_trusted(&manifest)?;
+                self.check_revenue_claim(&manifest).await?;
+                self.check_publisher_bond(&manifest).await?;
                 manifest.provenance.root_l0l1.clone()
             } else if let Some(announce) = self.state.get_announcement(hash) {
                 vec![ProvenanceEntry::new(
@@ -361,37 +479,19 @@ where
                     UNKNOWN_PEER_ID,
                     Visibility::Shared,
                 )]
+            } else if capability.is_some() {
+                vec![ProvenanceEntry::new(*hash, *owner, Visibility::Private)]
             } else {
                 vec![]
             };
 
-            // Create signed payment
-            let (payment, nonce) = create_signed_payment(
-                private_key,
-                &channel,
-                payment_amount,
-                *owner,
-                *hash,
-                provenance,
-            );
-
-            (payment, nonce)
+            // Create signed payment, recording its nonce before sending
+            let (payment, nonce) =
+                self.sign_tracked_payment(owner, &channel, payment_amount, *hash, provenance)?;
+            (Some(payment), nonce)
         } else {
-            // Free content - use placeholder payment (no signature needed)
-            let payment_id =
-                content_hash(&[hash.0.as_slice(), &owner.0, &timestamp.to_be_bytes()].concat());
-
-            let payment = Payment::new(
-                payment_id,
-                Hash([0u8; 32]),
-                0,
-                *owner,
-                *hash,
-                vec![],
-                timestamp,
-                Signature::from_bytes([0u8; 64]),
-            );
-            (payment, 1u64)
+            // Free content - no payment, so no channel or settlement
+            (None, 0)
         };
 
         let request = QueryRequestPayload {
@@ -400,24 +500,38 @@ where
             payment: payment.clone(),
             version_spec: None,
             payment_nonce,
+            capability,
+            recipient_key: self.recipient_key(),
+            challenge_response: None,
+            request_id: Some(request_id_or_new()),
+            section: None,
+            unit: None,
+            referrer: current_referrer(),
+            entitlement: None,
         };
 
-        let response = network.send_query(libp2p_peer, request).await?;
+        let mut response = self
+            .send_query_answering_challenge(network, libp2p_peer, request)
+            .await?;
 
-        // Verify content hash
+        // Decrypt restricted content, then verify the plaintext hash
+        self.open_response(&mut response)?;
         if !verify_content_hash(&response.content, hash) {
+            self.handle_bad_content(owner, hash, &response).await;
             return Err(OpsError::ContentHashMismatch);
         }
+        self.check_remote_metadata(&mut response.manifest);
 
         // Update channel balance after successful payment
-        if payment_amount > 0 {
+        if let Some(payment) = payment {
             self.update_payment_channel(owner, payment)?;
         }
+        self.screen_remote_content(&response.manifest)?;
 
         // Cache the content
         let cached = CachedContent::new(
             response.hash,
-            response.content.clone(),
+            response.content.to_vec(),
             response.manifest.owner,
             timestamp,
             response.payment_receipt.clone(),
@@ -425,18 +539,27 @@ where
         self.state.cache.cache(cached)?;
 
         // Also store the manifest for future reference
-        self.state.manifests.store(&response.manifest)?;
+        self.store_remote_manifest(&response.manifest)?;
+
+        // Cache items delivered with a collection bundle
+        let bundle = self.cache_bundle(
+            &response.content,
+            response.bundle,
+            &response.payment_receipt,
+            timestamp,
+        )?;
 
         Ok(QueryResponse {
-            content: response.content,
+            content: response.content.into_vec(),
             manifest: response.manifest,
             receipt: response.payment_receipt,
+            bundle,
         })
     }
 
     /// Fetch content from a DHT announcement.
     async fn fetch_content_from_dht_announce(
-        &mut self,
+        &self,
         hash: &Hash,
         announce: &nodalync_wire::AnnouncePayload,
         payment_amount: Amount,
@@ -494,8 +617,10 @@ where
         for addr_str in &announce.addresses {
             if let Ok(addr) = addr_str.parse::<nodalync_net::Multiaddr>() {
                 if network.dial(addr.clone()).await.is_ok() {
-                    // Try all connected peers
-                    for libp2p_peer in network.connected_peers() {
+                    // Try all connected peers, best ranked first
+                    let mut peers = network.connected_peers();
+                    self.rank_providers(network, &mut peers);
+                    for libp2p_peer in peers {
                         if let Some(response) = self
                             .try_query_peer(hash, libp2p_peer, payment_amount, network)
                             .await?
@@ -511,8 +636,11 @@ where
     }
 
     /// Helper to try querying a specific peer for content.
-    async fn try_query_peer(
-        &mut self,
+    ///
+    /// Returns `None` if the peer can't serve it, or the peer's error if
+    /// it names alternative providers, for the caller to try them.
+    pub(crate) async fn try_query_peer(
+        &self,
         hash: &Hash,
         libp2p_peer: nodalync_net::PeerId,
         payment_amount: Amount,
@@ -548,8 +676,7 @@ where
                         .try_query_peer_with_payment(
                             hash,
                             libp2p_peer,
-                            payment_amount,
-                            payment,
+                            Some(payment),
                             1u64,
                             network,
                         )
@@ -558,97 +685,99 @@ where
             };
 
             // Require private key for signing
-            let private_key = match self.private_key() {
-                Some(pk) => pk,
-                None => {
-                    // No private key - use placeholder and let server reject
-                    let payment_id =
-                        content_hash(&[hash.0.as_slice(), &timestamp.to_be_bytes()].concat());
-                    let payment = Payment::new(
-                        payment_id,
-                        channel.channel_id,
-                        payment_amount,
-                        recipient,
-                        *hash,
-                        vec![],
-                        timestamp,
-                        Signature::from_bytes([0u8; 64]),
-                    );
-                    return self
-                        .try_query_peer_with_payment(
-                            hash,
-                            libp2p_peer,
-                            payment_amount,
-                            payment,
-                            channel.nonce + 1,
-                            network,
-                        )
-                        .await;
-                }
-            };
+            if self.private_key().is_none() {
+                // No private key - use placeholder and let server reject
+                let payment_id =
+                    content_hash(&[hash.0.as_slice(), &timestamp.to_be_bytes()].concat());
+                let payment = Payment::new(
+                    payment_id,
+                    channel.channel_id,
+                    payment_amount,
+                    recipient,
+                    *hash,
+                    vec![],
+                    timestamp,
+                    Signature::from_bytes([0u8; 64]),
+                );
+                return self
+                    .try_query_peer_with_payment(
+                        hash,
+                        libp2p_peer,
+                        Some(payment),
+                        channel.nonce + 1,
+                        network,
+                    )
+                    .await;
+            }
 
-            // Get provenance from announcement or local manifest
-            let provenance = if let Some(announce) = self.state.get_announcement(hash) {
+            // Get provenance from the publisher's manifest (derived content and
+            // collections pay several roots), falling back to the announcement
+            // or local manifest
+            let preview = network
+                .send_preview_request(libp2p_peer, PreviewRequestPayload { hash: *hash })
+                .await
+                .ok()
+                .filter(|p| p.manifest.hash == *hash);
+            let provenance = if let Some(preview) = preview {
+                if let Some(pointer) = &preview.canonical {
+                    self.accept_canonical_version(pointer, &preview.manifest.owner);
+                }
+                if !preview.forks.is_empty() {
+                    tracing::warn!(hash = %hash, forks = preview.forks.len(), "Paying for a forked version");
+                }
+                // The owner quotes what sets a dynamic price, so a payment
+                // below it would only be refused after signing
+                check_remote_pricing(&preview.manifest)?;
+                let price = preview
+                    .manifest
+                    .economics
+                    .price_at(timestamp, preview.window_queries.unwrap_or(0));
+                if payment_amount < price {
+                    tracing::debug!(hash = %hash, price, payment_amount, "Payment below the current price");
+                    return Err(OpsError::PaymentInsufficient);
+                }
+                self.ensure_trusted(&preview.manifest)?;
+                self.check_revenue_claim(&preview.manifest).await?;
+                self.check_publisher_bond(&preview.manifest).await?;
+                preview.manifest.provenance.root_l0l1
+            } else if let Some(announce) = self.state.get_announcement(hash) {
                 vec![ProvenanceEntry::new(
                     announce.hash,
                     UNKNOWN_PEER_ID,
                     Visibility::Shared,
                 )]
             } else if let Some(manifest) = self.state.manifests.load(hash)? {
+                self.ensure_trusted(&manifest)?;
+                self.check_revenue_claim(&manifest).await?;
+                self.check_publisher_bond(&manifest).await?;
                 manifest.provenance.root_l0l1.clone()
             } else {
                 vec![]
             };
 
-            // Create signed payment
-            create_signed_payment(
-                private_key,
-                &channel,
-                payment_amount,
-                recipient,
-                *hash,
-                provenance,
-            )
+            // Create signed payment, recording its nonce before sending
+            let (payment, nonce) =
+                self.sign_tracked_payment(&recipient, &channel, payment_amount, *hash, provenance)?;
+            (Some(payment), nonce)
         } else {
-            // Free content - use placeholder payment (no signature needed)
-            let payment_id = content_hash(&[hash.0.as_slice(), &timestamp.to_be_bytes()].concat());
-
-            let payment = Payment::new(
-                payment_id,
-                Hash([0u8; 32]),
-                0,
-                recipient,
-                *hash,
-                vec![],
-                timestamp,
-                Signature::from_bytes([0u8; 64]),
-            );
-            (payment, 1u64)
+            // Free content - no payment, so no channel or settlement
+            (None, 0)
         };
 
-        self.try_query_peer_with_payment(
-            hash,
-            libp2p_peer,
-            payment_amount,
-            payment,
-            payment_nonce,
-            network,
-        )
-        .await
+        self.try_query_peer_with_payment(hash, libp2p_peer, payment, payment_nonce, network)
+            .await
     }
 
     /// Internal helper to execute a query with a prepared payment.
     async fn try_query_peer_with_payment(
-        &mut self,
+        &self,
         hash: &Hash,
         libp2p_peer: nodalync_net::PeerId,
-        payment_amount: Amount,
-        payment: Payment,
+        payment: Option<Payment>,
         payment_nonce: u64,
         network: &std::sync::Arc<dyn nodalync_net::Network>,
     ) -> OpsResult<Option<QueryResponse>> {
         let timestamp = current_timestamp();
-        let recipient = payment.recipient;
 
         let request = QueryRequestPayload {
             hash: *hash,
@@ -656,14 +785,33 @@ where
             payment: payment.clone(),
             version_spec: None,
             payment_nonce,
+            capability: None,
+            recipient_key: self.recipient_key(),
+            challenge_response: None,
+            request_id: Some(request_id_or_new()),
+            section: None,
+            unit: None,
+            referrer: current_referrer(),
+            entitlement: None,
         };
 
-        match network.send_query(libp2p_peer, request).await {
-            Ok(response) => {
-                // Verify content hash
-                if verify_content_hash(&response.content, hash) {
+        match self
+            .send_query_answering_challenge(network, libp2p_peer, request)
+            .await
+        {
+            Ok(mut response) => {
+                // Decrypt restricted content, then verify the plaintext hash
+                let opened = self.open_response(&mut response).is_ok();
+                if opened && !verify_content_hash(&response.content, hash) {
+                    if let Some(publisher) = network.nodalync_peer_id(&libp2p_peer) {
+                        self.handle_bad_content(&publisher, hash, &response).await;
+                    }
+                } else if opened {
+                    self.check_remote_metadata(&mut response.manifest);
+
                     // Update channel balance after successful payment
-                    if payment_amount > 0 {
+                    if let Some(payment) = payment {
+                        let recipient = payment.recipient;
                         if let Err(e) = self.update_payment_channel(&recipient, payment) {
                             tracing::warn!(
                                 "Failed to update channel after payment: {} (continuing)",
@@ -671,11 +819,12 @@ where
                             );
                         }
                     }
+                    self.screen_remote_content(&response.manifest)?;
 
                     // Cache the content
                     let cached = CachedContent::new(
                         response.hash,
-                        response.content.clone(),
+                        response.content.to_vec(),
                         response.manifest.owner,
                         timestamp,
                         response.payment_receipt.clone(),
@@ -683,12 +832,21 @@ where
                     self.state.cache.cache(cached)?;
 
                     // Store manifest
-                    self.state.manifests.store(&response.manifest)?;
+                    self.store_remote_manifest(&response.manifest)?;
+
+                    // Cache items delivered with a collection bundle
+                    let bundle = self.cache_bundle(
+                        &response.content,
+                        response.bundle,
+                        &response.payment_receipt,
+                        timestamp,
+                    )?;
 
                     return Ok(Some(QueryResponse {
-                        content: response.content,
+                        content: response.content.into_vec(),
                         manifest: response.manifest,
                         receipt: response.payment_receipt,
+                        bundle,
                     }));
                 }
             }
@@ -708,6 +866,15 @@ where
                     libp2p_peer_id,
                 });
             }
+            // The peer named other providers: the caller tries them
+            Err(e) if names_alternatives(&e) => {
+                tracing::debug!(
+                    "Peer {} refused the query with alternatives: {}",
+                    libp2p_peer,
+                    e
+                );
+                return Err(e.into());
+            }
             Err(e) => {
                 tracing::debug!("Failed to query peer {}: {}", libp2p_peer, e);
             }
@@ -724,6 +891,7 @@ where
     pub fn get_content_versions(&self, root_hash: &Hash) -> OpsResult<Vec<VersionInfo>> {
         // Get all manifests with same version root
         let manifests = self.state.manifests.get_versions(root_hash)?;
+        self.warn_on_broken_chain(root_hash, &manifests);
 
         // Convert to VersionInfo
         let version_infos: Vec<VersionInfo> = manifests
@@ -740,6 +908,114 @@ where
         Ok(version_infos)
     }
 
+    /// Fetch the latest version of content as a delta from a version we hold.
+    ///
+    /// 1. Loads the base version (owned or cached) and its manifest
+    /// 2. Sends a VersionRequest with `delta_from` to the owner
+    /// 3. Applies the delta and verifies the result against the new manifest
+    /// 4. Caches the new version and stores its manifest
+    ///
+    /// Returns `None` if `base` is already the latest version. Owners only
+    /// send deltas for free versions, and only owners advertising
+    /// compression are asked; otherwise this fails with `NotFound` and the
+    /// caller should query the latest version instead.
+    pub async fn fetch_latest_version(&self, base: &Hash) -> OpsResult<Option<QueryResponse>> {
+        let timestamp = current_timestamp();
+
+        // 1. Load the base version
+        let base_manifest = self
+            .state
+            .manifests
+            .load(base)?
+            .ok_or(OpsError::ManifestNotFound(*base))?;
+        let base_content = match self.state.content.load(base)? {
+            Some(content) => content,
+            None => {
+                self.state
+                    .cache
+                    .get(base)?
+                    .ok_or(OpsError::NotFound(*base))?
+                    .content
+            }
+        };
+
+        // 2. Ask the owner for a delta to the latest version
+        if !self.peer_supports(&base_manifest.owner, Capability::Compression) {
+            return Err(OpsError::NotFound(*base));
+        }
+        let network = self.network().cloned().ok_or(OpsError::NotFound(*base))?;
+        let libp2p_peer = network
+            .libp2p_peer_id(&base_manifest.owner)
+            .ok_or(OpsError::PeerIdNotFound)?;
+        let response = network
+            .send_version_request(
+                libp2p_peer,
+                VersionRequestPayload {
+                    version_root: base_manifest.version.root,
+                    delta_from: Some(*base),
+                    include_manifests: false,
+                },
+            )
+            .await?;
+
+        if let Some(pointer) = &response.canonical {
+            self.accept_canonical_version(pointer, &base_manifest.owner);
+        }
+        if response.latest == *base {
+            return Ok(None);
+        }
+        let delta = response.delta.ok_or(OpsError::NotFound(response.latest))?;
+        let mut manifest = delta.manifest;
+        if delta.base != *base
+            || manifest.hash != response.latest
+            || manifest.version.root != base_manifest.version.root
+        {
+            return Err(OpsError::invalid_operation(
+                "version delta does not match the request",
+            ));
+        }
+
+        // 3. Reconstruct and verify
+        let content = apply_delta(&base_content, &delta.delta, manifest.metadata.content_size)?;
+        if !verify_content_hash(&content, &manifest.hash) {
+            return Err(OpsError::ContentHashMismatch);
+        }
+        self.validator.validate_content(&content, &manifest)?;
+        self.check_remote_metadata(&mut manifest);
+        self.screen_remote_content(&manifest)?;
+
+        // 4. Cache with a free receipt (deltas are only served for free versions)
+        let receipt = PaymentReceipt {
+            payment_id: content_hash(
+                &[manifest.hash.0.as_slice(), &timestamp.to_be_bytes()].concat(),
+            ),
+            amount: 0,
+            timestamp,
+            channel_nonce: 0,
+            distributor_signature: Signature::from_bytes([0u8; 64]),
+            app_fee: 0,
+            app_fee_recipient: None,
+            referral_fee: 0,
+            referrer: None,
+        };
+        let cached = CachedContent::new(
+            manifest.hash,
+            content.clone(),
+            manifest.owner,
+            timestamp,
+            receipt.clone(),
+        );
+        self.state.cache.cache(cached)?;
+        self.store_remote_manifest(&manifest)?;
+
+        Ok(Some(QueryResponse {
+            content,
+            manifest,
+            receipt,
+            bundle: Vec::new(),
+        }))
+    }
+
     /// Check if content was queried (is in cache).
     pub fn is_content_cached(&self, hash: &Hash) -> bool {
         self.state.cache.is_cached(hash)
@@ -752,31 +1028,50 @@ where
 
     /// Search the network for content matching query.
     ///
+    /// Shorthand for [`search_network_filtered`](Self::search_network_filtered)
+    /// with at most a content type filter.
+    pub async fn search_network(
+        &self,
+        query: &str,
+        content_type: Option<ContentType>,
+        limit: u32,
+    ) -> OpsResult<Vec<NetworkSearchResult>> {
+        let filters = SearchFilters {
+            content_types: content_type.map(|ct| vec![ct]),
+            ..Default::default()
+        };
+        self.search_network_filtered(query, &filters, limit).await
+    }
+
+    /// Search the network for content matching query and filters.
+    ///
     /// Combines results from:
     /// 1. Local manifests
     /// 2. Cached announcements from network
-    /// 3. Connected peers via SEARCH protocol
+    /// 3. Connected peers via SEARCH protocol, queried concurrently
     ///
-    /// Results are deduplicated by hash (local takes precedence).
-    pub async fn search_network(
-        &mut self,
+    /// Results are deduplicated by hash (local takes precedence, then
+    /// cached). Content hidden by moderation is left out (see the
+    /// [`moderation`](crate::moderation) module). Among peers, the cheapest offer for each hash is kept; see
+    /// the [`search`](crate::search) module. Peers receive the filters with
+    /// the request; their results are checked again here in case the peer
+    /// ignores some of them.
+    pub async fn search_network_filtered(
+        &self,
         query: &str,
-        content_type: Option<ContentType>,
+        filters: &SearchFilters,
         limit: u32,
     ) -> OpsResult<Vec<NetworkSearchResult>> {
         let mut all_results = Vec::new();
         let mut seen_hashes = std::collections::HashSet::new();
 
         // 1. Search local manifests
-        let mut filter = ManifestFilter::new()
+        let filter = ManifestFilter::new()
+            .with_search_filters(filters)
             .with_text_query(query)
             .with_visibility(Visibility::Shared)
             .limit(limit);
 
-        if let Some(ct) = content_type {
-            filter = filter.with_content_type(ct);
-        }
-
         let local_manifests = self.state.manifests.list(filter)?;
         for manifest in local_manifests {
             if seen_hashes.insert(manifest.hash) {
@@ -794,90 +1089,99 @@ where
                     total_queries: manifest.economics.total_queries,
                     source: SearchSource::Local,
                     publisher_peer_id: None, // Local content, no remote peer
+                    latency_ms: None,
+                    verified_publisher: manifest.owner == self.peer_id(),
                 });
             }
         }
 
         // 2. Search cached announcements
-        let announcements = self.state.search_announcements(query, content_type, limit);
+        let announcements = self
+            .state
+            .search_announcements_filtered(query, filters, limit);
         for announce in announcements {
+            if self.is_hidden(&announce.hash) {
+                continue;
+            }
             if seen_hashes.insert(announce.hash) {
+                // Signed announcements name their publisher
+                let publisher = self
+                    .validator
+                    .validate_announcement(&announce)
+                    .ok()
+                    .flatten();
                 all_results.push(NetworkSearchResult {
                     hash: announce.hash,
                     title: announce.title.clone(),
                     content_type: announce.content_type,
                     price: announce.price,
-                    owner: UNKNOWN_PEER_ID,
+                    owner: publisher.unwrap_or(UNKNOWN_PEER_ID),
                     l1_summary: announce.l1_summary.clone(),
                     total_queries: 0,
                     source: SearchSource::Cached,
                     publisher_peer_id: announce.publisher_peer_id.clone(),
+                    latency_ms: None,
+                    verified_publisher: publisher.is_some(),
                 });
             }
         }
 
-        // 3. Query connected peers via SEARCH protocol
-        if let Some(network) = self.network().cloned() {
-            let search_payload = SearchPayload {
-                query: query.to_string(),
-                filters: content_type.map(|ct| SearchFilters {
-                    content_types: Some(vec![ct]),
-                    ..Default::default()
-                }),
-                limit,
-                offset: 0,
-            };
-
-            // Query up to 5 connected peers
-            for peer in network.connected_peers().iter().take(5) {
-                match network.send_search(*peer, search_payload.clone()).await {
-                    Ok(response) => {
-                        tracing::info!(
-                            peer = %peer,
-                            results_count = response.results.len(),
-                            "Received search response from peer"
-                        );
-                        for result in response.results {
-                            if seen_hashes.insert(result.hash) {
-                                // Create and cache an announcement so this content can be queried later
-                                // Use the publisher_addresses from the search result for robust reconnection
-                                tracing::info!(
-                                    hash = %result.hash,
-                                    title = %result.title,
-                                    publisher_addresses_count = result.publisher_addresses.len(),
-                                    publisher_addresses = ?result.publisher_addresses,
-                                    "Creating announcement from search result"
-                                );
-                                let announcement = nodalync_wire::AnnouncePayload {
-                                    hash: result.hash,
-                                    content_type: result.content_type,
-                                    title: result.title.clone(),
-                                    l1_summary: result.l1_summary.clone(),
-                                    price: result.price,
-                                    addresses: result.publisher_addresses.clone(),
-                                    publisher_peer_id: Some(peer.to_string()),
-                                };
-                                self.state.store_announcement(announcement);
-
-                                all_results.push(NetworkSearchResult {
-                                    hash: result.hash,
-                                    title: result.title.clone(),
-                                    content_type: result.content_type,
-                                    price: result.price,
-                                    owner: result.owner,
-                                    l1_summary: result.l1_summary.clone(),
-                                    total_queries: result.total_queries,
-                                    source: SearchSource::Peer,
-                                    publisher_peer_id: Some(peer.to_string()),
-                                });
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        tracing::debug!(peer = %peer, error = %e, "Peer search failed");
-                    }
-                }
+        // 3. Fan out to connected peers via SEARCH protocol
+        let search_payload = SearchPayload {
+            query: query.to_string(),
+            filters: (!filters.is_empty()).then(|| filters.clone()),
+            limit,
+            offset: 0,
+        };
+        for hit in self.fan_out_search(&search_payload, filters).await {
+            let result = hit.result;
+            if self.is_hidden(&result.hash) || !seen_hashes.insert(result.hash) {
+                continue;
             }
+            let publisher_peer_id = hit.peer.to_string();
+            // The owner answered for its own content
+            let verified_publisher = self
+                .network()
+                .and_then(|network| network.nodalync_peer_id(&hit.peer))
+                == Some(result.owner);
+
+            // Create and cache an announcement so this content can be queried later
+            // Use the publisher_addresses from the search result for robust reconnection
+            tracing::info!(
+                hash = %result.hash,
+                title = %result.title,
+                publisher_addresses_count = result.publisher_addresses.len(),
+                publisher_addresses = ?result.publisher_addresses,
+                "Creating announcement from search result"
+            );
+            let announcement = nodalync_wire::AnnouncePayload {
+                hash: result.hash,
+                content_type: result.content_type,
+                title: result.title.clone(),
+                l1_summary: result.l1_summary.clone(),
+                price: result.price,
+                addresses: result.publisher_addresses,
+                publisher_peer_id: Some(publisher_peer_id.clone()),
+                publisher_key: None,
+                signature: None,
+                delegation: None,
+            };
+            // Unsigned, so it never replaces an announcement we already hold
+            self.state.store_announcement_if_absent(announcement);
+
+            all_results.push(NetworkSearchResult {
+                hash: result.hash,
+                title: result.title,
+                content_type: result.content_type,
+                price: result.price,
+                owner: result.owner,
+                l1_summary: result.l1_summary,
+                total_queries: result.total_queries,
+                source: SearchSource::Peer,
+                publisher_peer_id: Some(publisher_peer_id),
+                latency_ms: Some(hit.latency_ms),
+                verified_publisher,
+            });
         }
 
         // Truncate to limit
@@ -919,7 +1223,7 @@ pub struct NetworkSearchResult {
     pub content_type: ContentType,
     /// Query price.
     pub price: Amount,
-    /// Content owner (may be UNKNOWN_PEER_ID for cached announcements).
+    /// Content owner (UNKNOWN_PEER_ID for unsigned cached announcements).
     pub owner: PeerId,
     /// L1 summary preview.
     pub l1_summary: L1Summary,
@@ -929,7 +1233,13 @@ pub struct NetworkSearchResult {
     pub source: SearchSource,
     /// Publisher peer ID (libp2p format, for dialing).
     /// Available for announcements; None for local content.
+    /// For peer results, the peer whose offer was kept.
     pub publisher_peer_id: Option<String>,
+    /// Response time of that peer, for peer results.
+    pub latency_ms: Option<u64>,
+    /// Whether `owner` is verified: our own content, an announcement signed
+    /// by its publisher, or a peer result returned by its owner.
+    pub verified_publisher: bool,
 }
 
 /// Extract primary topics from mentions.
@@ -974,7 +1284,7 @@ mod tests {
 
     #[test]
     fn test_extract_l1_summary() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         let content = b"Apple announced new products. Microsoft released updates. We found significant improvements.";
         let meta = Metadata::new("Tech News", content.len() as u64);
@@ -989,7 +1299,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_preview_content() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         let content = b"Test content for preview";
         let meta = Metadata::new("Preview Test", content.len() as u64);
@@ -1003,7 +1313,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_query_own_content() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         let content = b"Test content for query";
         let meta = Metadata::new("Query Test", content.len() as u64);
@@ -1017,7 +1327,7 @@ mod tests {
 
     #[test]
     fn test_get_versions() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create initial content
         let content1 = b"Version 1";
@@ -1039,7 +1349,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_query_insufficient_payment() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create content with price
         let content = b"Paid content";
@@ -1059,7 +1369,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_is_content_cached() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         let content = b"Cached content";
         let meta = Metadata::new("Cache Test", content.len() as u64);
@@ -1077,7 +1387,7 @@ mod tests {
 
     #[test]
     fn test_get_content_manifest_existing() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Create content (this also stores the manifest)
         let content = b"Manifest retrieval test";
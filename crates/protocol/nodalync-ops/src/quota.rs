@@ -0,0 +1,325 @@
+//! Daily quotas on the queries we serve each requester.
+//!
+//! An owner can limit how many queries, or how many bytes, each requester
+//! is served per UTC day: per content with a [`QuotaPolicy`] in
+//! `access.quota`, and across all content with [`OpsConfig::quota`]. Both
+//! apply when set:
+//!
+//! - Usage is counted per requester in the store, for the content and
+//!   node-wide, whenever a quota applies to the content served
+//! - A query that would take the requester past either quota, counting the
+//!   bytes it would be served, is refused with `QuotaExceeded`, carrying
+//!   when the quota resets; the query error tells it how long to wait
+//! - The check and the count happen together under the requester's lock,
+//!   and a query refused later on (say, for its payment) is given back
+//! - Free, paid, trial and re-download queries all count; the owner is
+//!   never limited
+//!
+//! Usage is only counted while a quota applies, so a quota set mid-day
+//! starts from what is served after it.
+//!
+//! [`OpsConfig::quota`]: crate::config::OpsConfig::quota
+
+use nodalync_crypto::{Hash, PeerId, Timestamp};
+use nodalync_store::{ManifestStore, QuotaStore, QuotaUsage};
+use nodalync_types::{Manifest, QuotaPolicy};
+use nodalync_valid::{validate_quota, Validator};
+use tracing::{debug, warn};
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, lock, NodeOperations};
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Set a daily quota on each requester of owned content; `None` lifts
+    /// it.
+    pub fn set_content_quota(&self, hash: &Hash, quota: Option<QuotaPolicy>) -> OpsResult<()> {
+        let manifest = self
+            .state
+            .manifests
+            .load(hash)?
+            .ok_or(OpsError::ManifestNotFound(*hash))?;
+
+        let mut access = manifest.access;
+        access.quota = quota;
+        self.set_content_access(hash, access)
+    }
+
+    /// Each requester's usage today, of one content hash or node-wide
+    /// with `None`, most queries first.
+    pub fn quota_usage(&self, hash: Option<&Hash>) -> OpsResult<Vec<(PeerId, QuotaUsage)>> {
+        let period = QuotaPolicy::period(current_timestamp());
+        Ok(self.state.quotas.peers(hash, period)?)
+    }
+
+    /// Forget a requester's usage today, giving it its full quotas again.
+    ///
+    /// Returns whether it had used any.
+    pub fn reset_quota(&self, peer: &PeerId) -> OpsResult<bool> {
+        Ok(self.state.quotas.reset(peer)?)
+    }
+
+    /// Reserve a query of `size` bytes against the requester's quotas on
+    /// the content and the node.
+    ///
+    /// Refuses a query that would take the requester past either quota;
+    /// otherwise counts it straight away, under the requester's lock, so
+    /// concurrent queries can't all pass the check. Returns `None` when no
+    /// quota applies; a query that isn't served gives its reservation back
+    /// with [`release_quota`](Self::release_quota).
+    pub(crate) fn reserve_quota(
+        &self,
+        requester: &PeerId,
+        manifest: &Manifest,
+        size: u64,
+        now: Timestamp,
+    ) -> OpsResult<Option<QuotaReservation>> {
+        if *requester == manifest.owner
+            || (manifest.access.quota.is_none() && self.config.quota.is_none())
+        {
+            return Ok(None);
+        }
+
+        let period = QuotaPolicy::period(now);
+        let peer_lock = self.channel_locks.for_peer(requester);
+        let _peer_guard = lock(&peer_lock);
+        if let Some(quota) = &manifest.access.quota {
+            let used = self
+                .state
+                .quotas
+                .usage(requester, Some(&manifest.hash), period)?;
+            validate_quota(quota, used.queries, used.bytes, size, now)?;
+        }
+        if let Some(quota) = &self.config.quota {
+            let used = self.state.quotas.usage(requester, None, period)?;
+            validate_quota(quota, used.queries, used.bytes, size, now)?;
+        }
+        self.state
+            .quotas
+            .record_use(requester, &manifest.hash, period, size)?;
+        debug!(hash = %manifest.hash, requester = %requester, bytes = size, "Counted quota use");
+
+        Ok(Some(QuotaReservation {
+            hash: manifest.hash,
+            period,
+            bytes: size,
+        }))
+    }
+
+    /// Give back the quota reserved for a query that wasn't served.
+    pub(crate) fn release_quota(&self, requester: &PeerId, reservation: QuotaReservation) {
+        if let Err(e) = self.state.quotas.release_use(
+            requester,
+            &reservation.hash,
+            reservation.period,
+            reservation.bytes,
+        ) {
+            warn!(hash = %reservation.hash, error = %e, "Failed to release quota use");
+        }
+    }
+}
+
+/// A query counted against a requester's quotas before it is served.
+#[derive(Debug)]
+pub(crate) struct QuotaReservation {
+    hash: Hash,
+    period: u64,
+    bytes: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OpsConfig;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_types::{ErrorCode, Metadata, QuotaLimit, Visibility, QUOTA_PERIOD_MS};
+    use nodalync_valid::ValidationError;
+    use nodalync_wire::QueryRequestPayload;
+    use tempfile::TempDir;
+
+    fn create_test_ops(config: OpsConfig) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+
+        let (_, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+
+        let ops = DefaultNodeOperations::with_config(state, peer_id, config);
+        (ops, temp_dir)
+    }
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    fn free_request(hash: Hash) -> QueryRequestPayload {
+        QueryRequestPayload {
+            hash,
+            query: None,
+            payment: None,
+            version_spec: None,
+            payment_nonce: 0,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        }
+    }
+
+    async fn publish_free(ops: &DefaultNodeOperations, content: &[u8]) -> Hash {
+        let hash = ops
+            .create_content(content, Metadata::new("Doc", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 0)
+            .await
+            .unwrap();
+        hash
+    }
+
+    #[tokio::test]
+    async fn test_content_quota() {
+        let (ops, _temp) = create_test_ops(OpsConfig::default());
+        let hash = publish_free(&ops, b"Limited reading").await;
+        let other = publish_free(&ops, b"Unlimited reading").await;
+        let requester = test_peer_id();
+
+        assert!(ops
+            .set_content_quota(&hash, Some(QuotaPolicy::default()))
+            .is_err());
+        ops.set_content_quota(&hash, Some(QuotaPolicy::default().with_queries(2)))
+            .unwrap();
+
+        for _ in 0..2 {
+            ops.handle_query_request(&requester, &free_request(hash))
+                .await
+                .unwrap();
+        }
+        let err = ops
+            .handle_query_request(&requester, &free_request(hash))
+            .await
+            .unwrap_err();
+        let OpsError::Validation(ValidationError::QuotaExceeded {
+            limit,
+            allowed,
+            resets_at,
+        }) = &err
+        else {
+            panic!("expected QuotaExceeded, got {err:?}");
+        };
+        assert_eq!((*limit, *allowed), (QuotaLimit::Queries, 2));
+        assert_eq!(resets_at % QUOTA_PERIOD_MS, 0);
+        assert_eq!(err.error_code(), ErrorCode::RateLimited);
+        let wait = err.retry_after_ms().unwrap();
+        assert!(wait > 0 && wait <= QUOTA_PERIOD_MS);
+
+        // Other requesters and other content are not limited
+        ops.handle_query_request(&test_peer_id(), &free_request(hash))
+            .await
+            .unwrap();
+        ops.handle_query_request(&requester, &free_request(other))
+            .await
+            .unwrap();
+
+        let usage = ops.quota_usage(Some(&hash)).unwrap();
+        assert_eq!(usage.len(), 2);
+        assert_eq!(
+            usage[0],
+            (
+                requester,
+                QuotaUsage {
+                    queries: 2,
+                    bytes: 30
+                }
+            )
+        );
+
+        // A reset gives the requester its quota back
+        assert!(ops.reset_quota(&requester).unwrap());
+        ops.handle_query_request(&requester, &free_request(hash))
+            .await
+            .unwrap();
+
+        ops.set_content_quota(&hash, None).unwrap();
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        assert!(manifest.access.quota.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_node_quota() {
+        let config = OpsConfig::default().with_quota(QuotaPolicy::default().with_bytes(20));
+        let (ops, _temp) = create_test_ops(config);
+        let first = publish_free(&ops, b"Twelve bytes").await;
+        let second = publish_free(&ops, b"Another twelve").await;
+        let third = publish_free(&ops, b"Eight by").await;
+        let requester = test_peer_id();
+
+        // A query whose content would take the requester past the byte
+        // limit is refused, whatever content it is for; smaller content
+        // that still fits is served
+        ops.handle_query_request(&requester, &free_request(first))
+            .await
+            .unwrap();
+        let result = ops
+            .handle_query_request(&requester, &free_request(second))
+            .await;
+        assert!(matches!(
+            result,
+            Err(OpsError::Validation(ValidationError::QuotaExceeded {
+                limit: QuotaLimit::Bytes,
+                allowed: 20,
+                ..
+            }))
+        ));
+        let response = ops
+            .handle_query_request(&requester, &free_request(third))
+            .await
+            .unwrap();
+        assert_eq!(response.content.len(), 8);
+
+        let usage = ops.quota_usage(None).unwrap();
+        assert_eq!(
+            usage[0].1,
+            QuotaUsage {
+                queries: 2,
+                bytes: 20
+            }
+        );
+
+        // A reservation for a query that isn't served is given back
+        let other = test_peer_id();
+        let manifest = ops.get_content_manifest(&second).unwrap().unwrap();
+        let reservation = ops
+            .reserve_quota(&other, &manifest, 14, current_timestamp())
+            .unwrap()
+            .unwrap();
+        assert!(ops
+            .reserve_quota(&other, &manifest, 14, current_timestamp())
+            .is_err());
+        ops.release_quota(&other, reservation);
+        ops.handle_query_request(&other, &free_request(second))
+            .await
+            .unwrap();
+        let usage = ops.quota_usage(Some(&second)).unwrap();
+        assert_eq!(
+            usage,
+            vec![(
+                other,
+                QuotaUsage {
+                    queries: 1,
+                    bytes: 14
+                }
+            )]
+        );
+    }
+}
@@ -0,0 +1,403 @@
+//! Channel rebalancing.
+//!
+//! Payment channels drift as queries flow in one direction: a channel where
+//! we mostly pay ends up depleted (we can no longer pay that peer), while a
+//! channel where we mostly earn ends up saturated (the peer can no longer pay
+//! us). This module measures that skew across open channels and builds a
+//! plan to even it out.
+//!
+//! Two kinds of action are planned:
+//!
+//! - **Circular payments** pair a saturated channel with a depleted one and
+//!   move value around the loop `us → from → … → to → us`. They need the
+//!   counterparties to route the payment, so they are suggested only.
+//! - **Close/reopen** settles a depleted channel and opens a fresh one with
+//!   the default deposit. This is the only action performed automatically,
+//!   and only when `RebalanceConfig::auto_rebalance` is enabled.
+
+use nodalync_crypto::{Hash, PeerId, PrivateKey};
+use nodalync_store::ChannelStore;
+use nodalync_types::{Amount, Channel};
+use nodalync_valid::Validator;
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::NodeOperations;
+
+/// Balance skew of a single open channel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelSkew {
+    /// Channel counterparty.
+    pub peer: PeerId,
+    /// Channel identifier.
+    pub channel_id: Hash,
+    /// Our balance.
+    pub my_balance: Amount,
+    /// Counterparty's balance.
+    pub their_balance: Amount,
+    /// Our share of the channel capacity (0.0 - 1.0).
+    pub outbound_ratio: f64,
+}
+
+impl ChannelSkew {
+    fn from_channel(peer: PeerId, channel: &Channel) -> Option<Self> {
+        let capacity = channel.my_balance.checked_add(channel.their_balance)?;
+        if capacity == 0 {
+            return None;
+        }
+        Some(Self {
+            peer,
+            channel_id: channel.channel_id,
+            my_balance: channel.my_balance,
+            their_balance: channel.their_balance,
+            outbound_ratio: channel.my_balance as f64 / capacity as f64,
+        })
+    }
+
+    /// Total channel capacity.
+    pub fn capacity(&self) -> Amount {
+        self.my_balance + self.their_balance
+    }
+
+    /// Amount our balance is above an even split.
+    fn excess(&self) -> Amount {
+        self.my_balance.saturating_sub(self.capacity() / 2)
+    }
+
+    /// Amount our balance is below an even split.
+    fn deficit(&self) -> Amount {
+        (self.capacity() / 2).saturating_sub(self.my_balance)
+    }
+}
+
+/// A single step of a rebalance plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RebalanceAction {
+    /// Move `amount` of outbound capacity from the channel with `from` to
+    /// the channel with `to` via a circular payment. Suggested only.
+    Circular {
+        /// Saturated channel to pay out of.
+        from: PeerId,
+        /// Depleted channel to receive into.
+        to: PeerId,
+        /// Amount to move.
+        amount: Amount,
+    },
+    /// Close the channel with `peer` and reopen it with `deposit`.
+    Reopen {
+        /// Depleted channel counterparty.
+        peer: PeerId,
+        /// Deposit for the new channel.
+        deposit: Amount,
+    },
+}
+
+/// Rebalancing plan for all open channels.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RebalancePlan {
+    /// Channels we hold almost all of (the peer can't pay us).
+    pub saturated: Vec<ChannelSkew>,
+    /// Channels we hold almost none of (we can't pay the peer).
+    pub depleted: Vec<ChannelSkew>,
+    /// Planned actions, circular payments first.
+    pub actions: Vec<RebalanceAction>,
+}
+
+impl RebalancePlan {
+    /// Whether no action is needed.
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+/// Outcome of one performed rebalance action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebalanceOutcome {
+    /// The action that was attempted.
+    pub action: RebalanceAction,
+    /// Whether it was carried out.
+    pub performed: bool,
+    /// Why it was skipped or failed, if it was.
+    pub reason: Option<String>,
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Build a rebalance plan for all open channels.
+    ///
+    /// Saturated channels are paired with depleted ones (largest first) as
+    /// circular payments of at least `min_amount`. Depleted channels left
+    /// with an uncovered deficit get a close/reopen action.
+    pub fn plan_rebalance(&self) -> OpsResult<RebalancePlan> {
+        let config = &self.config.rebalance;
+        let high = config.skew_threshold;
+        let low = 1.0 - config.skew_threshold;
+
+        let mut plan = RebalancePlan::default();
+        for (peer, channel) in self.state.channels.list_open()? {
+            // Channels already being closed are left alone
+            if channel.pending_close.is_some() || channel.pending_dispute.is_some() {
+                continue;
+            }
+            let Some(skew) = ChannelSkew::from_channel(peer, &channel) else {
+                continue;
+            };
+            if skew.outbound_ratio > high {
+                plan.saturated.push(skew);
+            } else if skew.outbound_ratio < low {
+                plan.depleted.push(skew);
+            }
+        }
+        plan.saturated
+            .sort_by_key(|s| std::cmp::Reverse(s.excess()));
+        plan.depleted
+            .sort_by_key(|s| std::cmp::Reverse(s.deficit()));
+
+        let mut excess: Vec<Amount> = plan.saturated.iter().map(ChannelSkew::excess).collect();
+        for depleted in &plan.depleted {
+            let mut deficit = depleted.deficit();
+            for (source, available) in plan.saturated.iter().zip(excess.iter_mut()) {
+                let amount = deficit.min(*available);
+                if amount == 0 || amount < config.min_amount {
+                    continue;
+                }
+                plan.actions.push(RebalanceAction::Circular {
+                    from: source.peer,
+                    to: depleted.peer,
+                    amount,
+                });
+                *available -= amount;
+                deficit -= amount;
+            }
+
+            if deficit >= config.min_amount {
+                plan.actions.push(RebalanceAction::Reopen {
+                    peer: depleted.peer,
+                    deposit: self.config.channel.default_deposit,
+                });
+            }
+        }
+
+        // Keep circular suggestions ahead of close/reopen
+        plan.actions
+            .sort_by_key(|a| matches!(a, RebalanceAction::Reopen { .. }));
+
+        Ok(plan)
+    }
+
+    /// Perform the close/reopen actions of a plan.
+    ///
+    /// Circular payments are reported as skipped since they need routing
+    /// through the counterparties. A channel is only reopened after a
+    /// successful cooperative close with no unsettled payments left.
+    pub async fn execute_rebalance(
+        &self,
+        plan: &RebalancePlan,
+        private_key: &PrivateKey,
+    ) -> OpsResult<Vec<RebalanceOutcome>> {
+        let mut outcomes = Vec::with_capacity(plan.actions.len());
+
+        for action in &plan.actions {
+            let result = match action {
+                RebalanceAction::Circular { .. } => {
+                    Err("circular payments need routing by the counterparties".to_string())
+                }
+                RebalanceAction::Reopen { peer, deposit } => self
+                    .reopen_payment_channel(peer, *deposit, private_key)
+                    .await
+                    .map_err(|e| e.to_string()),
+            };
+
+            if let Err(reason) = &result {
+                tracing::debug!(action = ?action, reason = %reason, "Rebalance action skipped");
+            }
+            outcomes.push(RebalanceOutcome {
+                action: action.clone(),
+                performed: result.is_ok(),
+                reason: result.err(),
+            });
+        }
+
+        Ok(outcomes)
+    }
+
+    /// Plan and, if `auto_rebalance` is enabled, perform a rebalance.
+    ///
+    /// Returns `None` when automatic rebalancing is disabled.
+    pub async fn auto_rebalance_channels(
+        &self,
+        private_key: &PrivateKey,
+    ) -> OpsResult<Option<Vec<RebalanceOutcome>>> {
+        if !self.config.rebalance.auto_rebalance {
+            return Ok(None);
+        }
+        let plan = self.plan_rebalance()?;
+        if plan.is_empty() {
+            return Ok(Some(Vec::new()));
+        }
+        self.execute_rebalance(&plan, private_key).await.map(Some)
+    }
+
+    /// Close a channel cooperatively and open a new one with `deposit`.
+    async fn reopen_payment_channel(
+        &self,
+        peer: &PeerId,
+        deposit: Amount,
+        private_key: &PrivateKey,
+    ) -> OpsResult<()> {
+        let result = self.close_payment_channel(peer, private_key).await?;
+        if !result.is_success() {
+            return Err(OpsError::invalid_operation(
+                "cooperative close did not complete",
+            ));
+        }
+
+        if !self.state.channels.get_pending_payments(peer)?.is_empty() {
+            return Err(OpsError::invalid_operation(
+                "closed channel still has unsettled payments",
+            ));
+        }
+
+        // Drop the closed channel so a new one can be opened with the peer
+        self.state.channels.delete(peer)?;
+        let channel = self.open_payment_channel(peer, deposit).await?;
+
+        tracing::info!(
+            peer = %peer,
+            channel_id = %channel.channel_id,
+            deposit = deposit,
+            "Reopened channel to rebalance"
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{OpsConfig, RebalanceConfig};
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_store::NodeStateConfig;
+    use tempfile::TempDir;
+
+    const HBAR: Amount = 1_0000_0000;
+
+    fn create_test_ops(rebalance: RebalanceConfig) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let config = NodeStateConfig::new(temp_dir.path());
+        let state = nodalync_store::NodeState::open(config).unwrap();
+
+        let (_, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+
+        let config = OpsConfig::default().with_rebalance(rebalance);
+        let ops = DefaultNodeOperations::with_config(state, peer_id, config);
+        (ops, temp_dir)
+    }
+
+    fn open_channel(ops: &mut DefaultNodeOperations, mine: Amount, theirs: Amount) -> PeerId {
+        let (_, public_key) = generate_identity();
+        let peer = peer_id_from_public_key(&public_key);
+        let channel_id = content_hash(&peer.0);
+        ops.accept_payment_channel(&channel_id, &peer, theirs, mine)
+            .unwrap();
+        peer
+    }
+
+    #[test]
+    fn test_plan_balanced_channels() {
+        let (mut ops, _temp) = create_test_ops(RebalanceConfig::default());
+        open_channel(&mut ops, 500 * HBAR, 500 * HBAR);
+        open_channel(&mut ops, 700 * HBAR, 300 * HBAR);
+
+        let plan = ops.plan_rebalance().unwrap();
+        assert!(plan.is_empty());
+        assert!(plan.saturated.is_empty());
+        assert!(plan.depleted.is_empty());
+    }
+
+    #[test]
+    fn test_plan_pairs_saturated_with_depleted() {
+        let (mut ops, _temp) = create_test_ops(RebalanceConfig::default());
+        let rich = open_channel(&mut ops, 950 * HBAR, 50 * HBAR);
+        let poor = open_channel(&mut ops, 50 * HBAR, 950 * HBAR);
+
+        let plan = ops.plan_rebalance().unwrap();
+        assert_eq!(plan.saturated.len(), 1);
+        assert_eq!(plan.depleted.len(), 1);
+        assert_eq!(
+            plan.actions,
+            vec![RebalanceAction::Circular {
+                from: rich,
+                to: poor,
+                amount: 450 * HBAR,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_plan_reopens_uncovered_deficit() {
+        let (mut ops, _temp) = create_test_ops(RebalanceConfig::default());
+        let rich = open_channel(&mut ops, 900 * HBAR, 100 * HBAR);
+        let poor = open_channel(&mut ops, 0, 2000 * HBAR);
+
+        let plan = ops.plan_rebalance().unwrap();
+        assert_eq!(plan.actions.len(), 2);
+        assert_eq!(
+            plan.actions[0],
+            RebalanceAction::Circular {
+                from: rich,
+                to: poor,
+                amount: 400 * HBAR,
+            }
+        );
+        assert_eq!(
+            plan.actions[1],
+            RebalanceAction::Reopen {
+                peer: poor,
+                deposit: ops.config.channel.default_deposit,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_auto_rebalance_requires_cooperative_close() {
+        let (private_key, _) = generate_identity();
+
+        // Disabled by default
+        let (mut ops, _temp) = create_test_ops(RebalanceConfig::default());
+        open_channel(&mut ops, 0, 1000 * HBAR);
+        assert!(ops
+            .auto_rebalance_channels(&private_key)
+            .await
+            .unwrap()
+            .is_none());
+
+        // Enabled, but the peer can't be reached to co-sign the close
+        let config = RebalanceConfig::default().with_auto_rebalance(true);
+        let (mut ops, _temp) = create_test_ops(config);
+        let poor = open_channel(&mut ops, 0, 1000 * HBAR);
+
+        let outcomes = ops
+            .auto_rebalance_channels(&private_key)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(outcomes.len(), 1);
+        assert!(!outcomes[0].performed);
+        assert!(outcomes[0].reason.is_some());
+
+        // The depleted channel is left in place rather than dropped
+        let channel = ops.get_payment_channel(&poor).unwrap().unwrap();
+        assert!(channel.is_open());
+        assert_eq!(channel.their_balance, 1000 * HBAR);
+
+        // Its close is now pending, so it drops out of later plans
+        assert!(ops.plan_rebalance().unwrap().is_empty());
+    }
+}
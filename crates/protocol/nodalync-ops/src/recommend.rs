@@ -0,0 +1,362 @@
+//! Content recommendations.
+//!
+//! Builds an interest profile from the user's L2 entity graphs and recent
+//! query history, then scores stored network announcements against it to
+//! produce a "for you" feed.
+//!
+//! Profile terms are normalized phrases (lowercase, punctuation collapsed to
+//! single spaces):
+//!
+//! - L2 entity labels and aliases, weighted by `GRAPH_TERM_WEIGHT` times the
+//!   entity's confidence
+//! - Tags and title keywords of recently queried content, `HISTORY_TERM_WEIGHT`
+//!   per query
+//!
+//! An announcement's score is the sum of the weights of the profile terms it
+//! matches in its title, primary topics, or preview mention entities. Content
+//! we own or have already queried is never recommended.
+
+use std::collections::{HashMap, HashSet};
+
+use nodalync_crypto::Hash;
+use nodalync_store::{CacheStore, ContentStore, ManifestFilter, ManifestStore};
+use nodalync_types::{Amount, ContentType, L2EntityGraph};
+use nodalync_valid::Validator;
+use nodalync_wire::AnnouncePayload;
+use tracing::debug;
+
+use crate::error::OpsResult;
+use crate::extraction::L1Extractor;
+use crate::node_ops::NodeOperations;
+
+/// Weight of an L2 entity term (scaled by the entity's confidence).
+pub const GRAPH_TERM_WEIGHT: f64 = 2.0;
+
+/// Weight of a term from one previously queried piece of content.
+pub const HISTORY_TERM_WEIGHT: f64 = 1.0;
+
+/// Title words shorter than this are not used as keywords.
+const MIN_KEYWORD_LEN: usize = 4;
+
+/// Common title words that say nothing about the topic.
+const STOPWORDS: &[&str] = &[
+    "about", "after", "also", "from", "have", "into", "more", "notes", "other", "over", "some",
+    "than", "that", "their", "there", "these", "this", "what", "when", "which", "with", "your",
+];
+
+/// A recommended piece of network content.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recommendation {
+    /// Content hash.
+    pub hash: Hash,
+    /// Announced title.
+    pub title: String,
+    /// Content type.
+    pub content_type: ContentType,
+    /// Query price.
+    pub price: Amount,
+    /// Publisher's libp2p peer ID, if announced.
+    pub publisher_peer_id: Option<String>,
+    /// Relevance score (sum of matched term weights).
+    pub score: f64,
+    /// Profile terms that matched, highest weight first.
+    pub matched_terms: Vec<String>,
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Get recommended network content, most relevant first.
+    ///
+    /// Returns an empty feed when there is no L2 graph or query history to
+    /// base recommendations on.
+    pub fn recommended_content(&self, limit: usize) -> OpsResult<Vec<Recommendation>> {
+        let profile = self.interest_profile()?;
+        if profile.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut recommendations = Vec::new();
+        for announcement in self.state.list_announcements() {
+            if self.state.manifests.load(&announcement.hash)?.is_some()
+                || self.state.cache.is_cached(&announcement.hash)
+            {
+                continue;
+            }
+            if let Some(rec) = score_announcement(&profile, announcement) {
+                if rec.score >= self.config.recommendation.min_score {
+                    recommendations.push(rec);
+                }
+            }
+        }
+
+        recommendations.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.title.cmp(&b.title))
+        });
+        recommendations.truncate(limit);
+        Ok(recommendations)
+    }
+
+    /// Build the weighted interest profile from L2 graphs and query history.
+    fn interest_profile(&self) -> OpsResult<HashMap<String, f64>> {
+        let mut profile: HashMap<String, f64> = HashMap::new();
+
+        // L2 entity graphs
+        let filter = ManifestFilter::new()
+            .with_owner(self.peer_id())
+            .with_content_type(ContentType::L2);
+        for manifest in self.state.manifests.list(filter)? {
+            let Some(content) = self.state.content.load(&manifest.hash)? else {
+                continue;
+            };
+            let graph: L2EntityGraph = match serde_json::from_slice(&content) {
+                Ok(graph) => graph,
+                Err(e) => {
+                    debug!(hash = %manifest.hash, error = %e, "Skipping unreadable L2 graph");
+                    continue;
+                }
+            };
+            for entity in &graph.entities {
+                let weight = GRAPH_TERM_WEIGHT * f64::from(entity.confidence.clamp(0.0, 1.0));
+                for label in std::iter::once(&entity.canonical_label).chain(&entity.aliases) {
+                    add_term(&mut profile, label, weight);
+                }
+            }
+        }
+
+        // Query history
+        let history = self
+            .state
+            .cache
+            .recent(self.config.recommendation.history_limit)?;
+        for (hash, _) in history {
+            let Some(manifest) = self.state.manifests.load(&hash)? else {
+                continue;
+            };
+            let mut terms: HashSet<String> =
+                keywords(&manifest.metadata.title).into_iter().collect();
+            terms.extend(manifest.metadata.tags.iter().map(|t| normalize(t)));
+            for term in terms {
+                add_term(&mut profile, &term, HISTORY_TERM_WEIGHT);
+            }
+        }
+
+        Ok(profile)
+    }
+}
+
+/// Score an announcement against an interest profile.
+///
+/// Returns `None` if no profile term matches.
+fn score_announcement(
+    profile: &HashMap<String, f64>,
+    announcement: AnnouncePayload,
+) -> Option<Recommendation> {
+    let title = normalize(&announcement.title);
+    let padded_title = format!(" {} ", title);
+    let summary = &announcement.l1_summary;
+    let terms: HashSet<String> = summary
+        .primary_topics
+        .iter()
+        .chain(summary.preview_mentions.iter().flat_map(|m| &m.entities))
+        .map(|t| normalize(t))
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    let mut matched: Vec<(&String, f64)> = profile
+        .iter()
+        .filter(|(term, _)| terms.contains(*term) || padded_title.contains(&format!(" {} ", term)))
+        .map(|(term, weight)| (term, *weight))
+        .collect();
+    if matched.is_empty() {
+        return None;
+    }
+    matched.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+    Some(Recommendation {
+        hash: announcement.hash,
+        title: announcement.title,
+        content_type: announcement.content_type,
+        price: announcement.price,
+        publisher_peer_id: announcement.publisher_peer_id,
+        score: matched.iter().map(|(_, w)| w).sum(),
+        matched_terms: matched.into_iter().map(|(t, _)| t.clone()).collect(),
+    })
+}
+
+/// Add weight to a profile term, ignoring empty terms.
+fn add_term(profile: &mut HashMap<String, f64>, term: &str, weight: f64) {
+    let term = normalize(term);
+    if !term.is_empty() && weight > 0.0 {
+        *profile.entry(term).or_insert(0.0) += weight;
+    }
+}
+
+/// Lowercase and collapse everything but letters and digits to single spaces.
+pub(crate) fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Topic keywords from a title.
+pub(crate) fn keywords(title: &str) -> Vec<String> {
+    normalize(title)
+        .split(' ')
+        .filter(|w| w.chars().count() >= MIN_KEYWORD_LEN && !STOPWORDS.contains(w))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_ops::{current_timestamp, DefaultNodeOperations};
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key, Signature};
+    use nodalync_store::{CachedContent, NodeStateConfig};
+    use nodalync_types::{Entity, L1Summary, Manifest, Metadata};
+    use nodalync_wire::PaymentReceipt;
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let config = NodeStateConfig::new(temp_dir.path());
+        let state = nodalync_store::NodeState::open(config).unwrap();
+
+        let (_, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+
+        let ops = DefaultNodeOperations::with_defaults(state, peer_id);
+        (ops, temp_dir)
+    }
+
+    fn store_l2(ops: &mut DefaultNodeOperations, entities: Vec<Entity>) {
+        let mut graph = L2EntityGraph::new(content_hash(b"graph"));
+        graph.entities = entities;
+        graph.sync_counts();
+        let content = serde_json::to_vec(&graph).unwrap();
+        let hash = ops.state.content.store(&content).unwrap();
+
+        let meta = Metadata::new("L2 Entity Graph", content.len() as u64);
+        let mut manifest = Manifest::new_l0(hash, ops.peer_id(), meta, current_timestamp());
+        manifest.content_type = ContentType::L2;
+        ops.state.manifests.store(&manifest).unwrap();
+    }
+
+    fn record_query(ops: &mut DefaultNodeOperations, title: &str, tags: &[&str]) -> Hash {
+        let (_, public_key) = generate_identity();
+        let owner = peer_id_from_public_key(&public_key);
+        let content = title.as_bytes().to_vec();
+        let hash = content_hash(&content);
+
+        let meta = Metadata::new(title, content.len() as u64)
+            .with_tags(tags.iter().map(|t| t.to_string()).collect());
+        let manifest = Manifest::new_l0(hash, owner, meta, current_timestamp());
+        ops.state.manifests.store(&manifest).unwrap();
+        ops.state
+            .cache
+            .cache(CachedContent::new(
+                hash,
+                content,
+                owner,
+                current_timestamp(),
+                PaymentReceipt {
+                    payment_id: content_hash(b"payment"),
+                    amount: 0,
+                    timestamp: current_timestamp(),
+                    channel_nonce: 0,
+                    distributor_signature: Signature::from_bytes([0u8; 64]),
+                    app_fee: 0,
+                    app_fee_recipient: None,
+                    referral_fee: 0,
+                    referrer: None,
+                },
+            ))
+            .unwrap();
+        hash
+    }
+
+    fn announce(ops: &DefaultNodeOperations, title: &str, topics: &[&str]) -> Hash {
+        let hash = content_hash(title.as_bytes());
+        let mut summary = L1Summary::empty(hash);
+        summary.primary_topics = topics.iter().map(|t| t.to_string()).collect();
+        ops.state.store_announcement(AnnouncePayload {
+            hash,
+            content_type: ContentType::L0,
+            title: title.to_string(),
+            l1_summary: summary,
+            price: 100,
+            addresses: vec![],
+            publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
+        });
+        hash
+    }
+
+    #[test]
+    fn test_no_profile_no_recommendations() {
+        let (ops, _temp) = create_test_ops();
+        announce(&ops, "Rust ownership explained", &["Rust"]);
+
+        assert!(ops.recommended_content(10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_recommendations_from_graph_and_history() {
+        let (mut ops, _temp) = create_test_ops();
+        store_l2(
+            &mut ops,
+            vec![
+                Entity::new("e1", "Machine Learning").with_aliases(vec!["ML".to_string()]),
+                Entity::new("e2", "Protein Folding").with_confidence(0.5),
+            ],
+        );
+        let queried = record_query(&mut ops, "Transformers for genomics", &["biology"]);
+
+        let ml = announce(&ops, "Intro to machine learning", &[]);
+        let folding = announce(&ops, "Structure prediction", &["protein folding"]);
+        let genomics = announce(&ops, "Genomics datasets", &["Biology"]);
+        let unrelated = announce(&ops, "Medieval poetry", &["literature"]);
+        // Content we already queried is not recommended again
+        announce(&ops, "Transformers for genomics", &["biology"]);
+
+        let feed = ops.recommended_content(10).unwrap();
+        let hashes: Vec<Hash> = feed.iter().map(|r| r.hash).collect();
+
+        // genomics matches "genomics" (title) and "biology" (topic): 2.0
+        // ml matches "machine learning" at full confidence: 2.0
+        // folding matches "protein folding" at half confidence: 1.0
+        assert_eq!(hashes.len(), 3);
+        assert!(hashes[..2].contains(&ml));
+        assert!(hashes[..2].contains(&genomics));
+        assert_eq!(hashes[2], folding);
+        assert!(!hashes.contains(&unrelated));
+        assert!(!hashes.contains(&queried));
+
+        let genomics_rec = feed.iter().find(|r| r.hash == genomics).unwrap();
+        assert_eq!(genomics_rec.score, 2.0);
+        assert_eq!(genomics_rec.matched_terms, vec!["biology", "genomics"]);
+
+        assert_eq!(ops.recommended_content(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_normalize_and_keywords() {
+        assert_eq!(
+            normalize("  Machine-Learning, 101! "),
+            "machine learning 101"
+        );
+        assert_eq!(
+            keywords("Notes about the Rust borrow checker"),
+            vec!["rust", "borrow", "checker"]
+        );
+    }
+}
@@ -0,0 +1,325 @@
+//! Preview redaction.
+//!
+//! A manifest's [`PreviewPolicy`] controls what its L1 summary reveals
+//! before payment. The preview summary is extracted from the content with
+//! the policy applied: redacted Markdown sections are left out, and text
+//! matching a redaction pattern is replaced with [`REDACTION_MARKER`].
+//!
+//! Only the preview summary leaves the node before payment: in preview
+//! responses, search results and announcements, and as the topics added to
+//! the tags on publish. Paid and authorized queries still get the full
+//! content.
+
+use nodalync_crypto::Hash;
+use nodalync_store::{ContentStore, ManifestStore};
+use nodalync_types::{L1Summary, Mention, PreviewPolicy, REDACTION_MARKER};
+use nodalync_valid::{ValidationError, Validator};
+use regex::Regex;
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::NodeOperations;
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Get the L1 summary other peers may see before paying.
+    ///
+    /// Extracts from the content with the manifest's preview policy
+    /// applied. Without a policy this is the same as
+    /// [`Self::extract_l1_summary`].
+    pub fn preview_summary(&self, hash: &Hash) -> OpsResult<L1Summary> {
+        let manifest = self
+            .state
+            .manifests
+            .load(hash)?
+            .ok_or(OpsError::ManifestNotFound(*hash))?;
+
+        let Some(policy) = manifest
+            .metadata
+            .preview_policy
+            .filter(|policy| !policy.is_empty())
+        else {
+            return self.extract_l1_summary(hash);
+        };
+
+        let content = self
+            .state
+            .content
+            .load(hash)?
+            .ok_or(OpsError::NotFound(*hash))?;
+
+        let patterns = compile_patterns(&policy)?;
+        let mime_type = manifest.metadata.mime_type.as_deref();
+
+        // Sections can only be cut from text; patterns are applied again to
+        // whatever the extractor produces, so binary formats are covered too
+        let mut mentions = match std::str::from_utf8(&content) {
+            Ok(text) => {
+                let redacted = redact_with(text, &policy.redact_sections, &patterns);
+                self.extractor.extract(redacted.as_bytes(), mime_type)?
+            }
+            Err(_) => self.extractor.extract(&content, mime_type)?,
+        };
+        for mention in &mut mentions {
+            redact_mention(mention, &patterns);
+        }
+
+        Ok(self.summarize_mentions(hash, &mentions))
+    }
+}
+
+/// Apply a preview policy to text.
+///
+/// Removes each Markdown section (with its subsections) whose heading
+/// matches one of `redact_sections`, ignoring case, then replaces every
+/// match of `redact_patterns` with [`REDACTION_MARKER`].
+pub fn redact_text(text: &str, policy: &PreviewPolicy) -> OpsResult<String> {
+    let patterns = compile_patterns(policy)?;
+    Ok(redact_with(text, &policy.redact_sections, &patterns))
+}
+
+fn redact_with(text: &str, sections: &[String], patterns: &[Regex]) -> String {
+    let text = remove_sections(text, sections);
+    replace_matches(&text, patterns)
+}
+
+fn compile_patterns(policy: &PreviewPolicy) -> OpsResult<Vec<Regex>> {
+    policy
+        .redact_patterns
+        .iter()
+        .map(|pattern| {
+            Regex::new(pattern).map_err(|e| {
+                OpsError::Validation(ValidationError::InvalidPreviewPolicy {
+                    reason: format!("pattern {:?}: {}", pattern, e),
+                })
+            })
+        })
+        .collect()
+}
+
+fn replace_matches(text: &str, patterns: &[Regex]) -> String {
+    let mut text = text.to_string();
+    for pattern in patterns {
+        text = pattern.replace_all(&text, REDACTION_MARKER).into_owned();
+    }
+    text
+}
+
+/// Drop the lines of every redacted section.
+///
+/// A section runs from its heading to the next heading of the same or a
+/// higher level.
+fn remove_sections(text: &str, sections: &[String]) -> String {
+    if sections.is_empty() {
+        return text.to_string();
+    }
+    let sections: Vec<String> = sections.iter().map(|s| s.trim().to_lowercase()).collect();
+
+    let mut result = String::with_capacity(text.len());
+    let mut skipping_level: Option<usize> = None;
+    for line in text.lines() {
+        match markdown_heading(line) {
+            Some((level, heading)) => {
+                if skipping_level.is_some_and(|skipping| level > skipping) {
+                    continue;
+                }
+                skipping_level = None;
+                if sections.contains(&heading.to_lowercase()) {
+                    skipping_level = Some(level);
+                    continue;
+                }
+            }
+            None if skipping_level.is_some() => continue,
+            None => {}
+        }
+        result.push_str(line);
+        result.push('\n');
+    }
+    result
+}
+
+/// Parse an ATX heading ("## Title") into its level and text.
+pub(crate) fn markdown_heading(line: &str) -> Option<(usize, &str)> {
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim_end()))
+}
+
+/// Redact pattern matches in an extracted mention.
+///
+/// Entities that match a pattern are dropped rather than masked, so they
+/// can't become topics.
+fn redact_mention(mention: &mut Mention, patterns: &[Regex]) {
+    if patterns.is_empty() {
+        return;
+    }
+    mention.content = replace_matches(&mention.content, patterns);
+    if let Some(quote) = mention.source_location.quote.as_mut() {
+        *quote = replace_matches(quote, patterns);
+    }
+    mention
+        .entities
+        .retain(|entity| !patterns.iter().any(|p| p.is_match(entity)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_types::{Metadata, Visibility};
+    use nodalync_wire::PreviewRequestPayload;
+    use tempfile::TempDir;
+
+    const REPORT: &str = "# Quarterly Report\n\
+        \n\
+        Revenue at Acme Corporation grew by 12 percent this quarter.\n\
+        \n\
+        ## Confidential\n\
+        \n\
+        The Falcon Project will launch with partner Globex next spring.\n\
+        \n\
+        ### Contacts\n\
+        \n\
+        Reach Jane Smith at jane@acme.example for details.\n\
+        \n\
+        ## Outlook\n\
+        \n\
+        Analysts at Initech expect steady growth. Call 555-0142 to subscribe.\n";
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (_, public_key) = generate_identity();
+        let ops = DefaultNodeOperations::with_defaults(state, peer_id_from_public_key(&public_key));
+        (ops, temp_dir)
+    }
+
+    fn policy() -> PreviewPolicy {
+        PreviewPolicy::new()
+            .with_section("confidential")
+            .with_pattern(r"\d{3}-\d{4}")
+            .with_pattern("Initech")
+    }
+
+    #[test]
+    fn test_redact_text() {
+        let redacted = redact_text(REPORT, &policy()).unwrap();
+
+        // The section and its subsection are gone; later sections remain
+        assert!(redacted.contains("Acme Corporation"));
+        assert!(!redacted.contains("Falcon"));
+        assert!(!redacted.contains("Confidential"));
+        assert!(!redacted.contains("jane@acme.example"));
+        assert!(redacted.contains("## Outlook"));
+
+        // Pattern matches are masked in place
+        assert!(redacted.contains("Analysts at [redacted] expect"));
+        assert!(redacted.contains("Call [redacted] to subscribe"));
+
+        // Headings need a space after the hashes
+        let text = "#Confidential\nKept.\n";
+        assert_eq!(redact_text(text, &policy()).unwrap(), text);
+
+        let invalid = PreviewPolicy::new().with_pattern("(unclosed");
+        assert!(matches!(
+            redact_text(REPORT, &invalid),
+            Err(OpsError::Validation(
+                ValidationError::InvalidPreviewPolicy { .. }
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_preview_leaves_node_redacted() {
+        let (ops, _temp) = create_test_ops();
+        let meta =
+            Metadata::new("Quarterly Report", REPORT.len() as u64).with_preview_policy(policy());
+        let hash = ops.create_content(REPORT.as_bytes(), meta).unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 100)
+            .await
+            .unwrap();
+
+        let leaks = |summary: &L1Summary| {
+            let json = serde_json::to_string(summary).unwrap();
+            ["Falcon", "Globex", "Jane", "Initech", "555-0142"]
+                .iter()
+                .any(|secret| json.contains(secret))
+        };
+
+        // The owner's own extraction still sees everything
+        let full = ops.extract_l1_summary(&hash).unwrap();
+        assert!(leaks(&full));
+
+        let preview = ops.preview_summary(&hash).unwrap();
+        assert!(!leaks(&preview));
+        assert!(preview.mention_count < full.mention_count);
+        assert!(preview
+            .primary_topics
+            .contains(&"Acme Corporation".to_string()));
+
+        // Preview responses carry the redacted summary, and not the rules
+        let (_, requester_key) = generate_identity();
+        let response = ops
+            .handle_preview_request(
+                &peer_id_from_public_key(&requester_key),
+                &PreviewRequestPayload { hash },
+            )
+            .unwrap();
+        assert_eq!(
+            response.l1_summary.preview_mentions,
+            preview.preview_mentions
+        );
+        assert!(!leaks(&response.l1_summary));
+        assert!(response.manifest.metadata.preview_policy.is_none());
+
+        // Topics added to the tags on publish come from the redacted summary
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        assert!(!manifest
+            .metadata
+            .tags
+            .iter()
+            .any(|tag| tag.contains("falcon") || tag.contains("initech")));
+    }
+
+    #[test]
+    fn test_set_preview_policy() {
+        let (ops, _temp) = create_test_ops();
+        let meta = Metadata::new("Quarterly Report", REPORT.len() as u64);
+        let hash = ops.create_content(REPORT.as_bytes(), meta).unwrap();
+        assert_eq!(
+            ops.preview_summary(&hash).unwrap().preview_mentions,
+            ops.extract_l1_summary(&hash).unwrap().preview_mentions
+        );
+
+        ops.set_content_preview_policy(&hash, Some(policy()))
+            .unwrap();
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        assert_eq!(manifest.metadata.preview_policy, Some(policy()));
+
+        // Invalid rules are rejected and leave the policy unchanged
+        let invalid = PreviewPolicy::new().with_pattern("(unclosed");
+        assert!(ops
+            .set_content_preview_policy(&hash, Some(invalid))
+            .is_err());
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        assert_eq!(manifest.metadata.preview_policy, Some(policy()));
+
+        // An empty policy clears it
+        ops.set_content_preview_policy(&hash, Some(PreviewPolicy::new()))
+            .unwrap();
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        assert!(manifest.metadata.preview_policy.is_none());
+    }
+}
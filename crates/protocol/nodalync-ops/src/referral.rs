@@ -0,0 +1,159 @@
+//! Referral attribution: paying the discovery nodes that bring in queries.
+//!
+//! An owner can offer a cut of each payment for its content
+//! (`economics.referral`) to the search node or recommender that referred
+//! the paying query:
+//!
+//! - The consumer names the referrer in the QUERY_REQUEST. Like the request
+//!   ID (see [`crate::trace`]), the referrer is kept in a task-local, so
+//!   every request sent beneath [`with_referrer`] names it, whether for
+//!   whole content, a section or a metered unit
+//! - The owner checks the referrer with `validate_referral` and takes the
+//!   cut off the top of the payment, next to the app fee, before the 95/5
+//!   split. The referrer gets its own entry in the settlement batch, and the
+//!   receipt shows the cut
+//!
+//! Free queries and trial reads pay nothing, so they ignore the referrer.
+
+use std::future::Future;
+
+use nodalync_crypto::{Hash, PeerId};
+use nodalync_store::ManifestStore;
+use nodalync_types::{Amount, ReferralPolicy};
+use nodalync_valid::{validate_referral_policy, Validator};
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+use crate::ops::QueryResponse;
+
+tokio::task_local! {
+    static REFERRER: PeerId;
+}
+
+/// The referrer of the operation running on this task, if any.
+pub(crate) fn current_referrer() -> Option<PeerId> {
+    REFERRER.try_with(|referrer| *referrer).ok()
+}
+
+/// Run `future` with every query it sends naming `referrer`.
+pub async fn with_referrer<F: Future>(referrer: PeerId, future: F) -> F::Output {
+    REFERRER.scope(referrer, future).await
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Offer a cut of each payment for owned content, in basis points, to
+    /// whoever refers the query; `None` stops offering one.
+    pub fn set_content_referral(&self, hash: &Hash, basis_points: Option<u32>) -> OpsResult<()> {
+        // Load manifest
+        let mut manifest = self
+            .state
+            .manifests
+            .load(hash)?
+            .ok_or(OpsError::ManifestNotFound(*hash))?;
+
+        // Verify ownership
+        if manifest.owner != self.peer_id() {
+            return Err(OpsError::AccessDenied);
+        }
+
+        let referral = basis_points.map(ReferralPolicy::new);
+        if let Some(policy) = &referral {
+            validate_referral_policy(policy)?;
+        }
+
+        // Update referral policy
+        manifest.economics.referral = referral;
+        manifest.updated_at = current_timestamp();
+
+        // Save manifest
+        self.state.manifests.update(&manifest)?;
+
+        Ok(())
+    }
+
+    /// Query content referred by `referrer`, who is paid the content's
+    /// referral cut out of `payment_amount`.
+    ///
+    /// The provider refuses the query if the content offers no referral
+    /// cut. See [`with_referrer`] to refer section and metered reads.
+    pub async fn query_referred(
+        &self,
+        hash: &Hash,
+        payment_amount: Amount,
+        referrer: PeerId,
+    ) -> OpsResult<QueryResponse> {
+        with_referrer(referrer, self.query_content(hash, payment_amount, None)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OpsConfig;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_types::{Metadata, Visibility};
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops = DefaultNodeOperations::with_config(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default(),
+        );
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    #[tokio::test]
+    async fn test_referrer_is_scoped_to_the_task() {
+        assert_eq!(current_referrer(), None);
+
+        let referrer = test_peer_id();
+        let seen = with_referrer(referrer, async {
+            tokio::task::yield_now().await;
+            current_referrer()
+        })
+        .await;
+        assert_eq!(seen, Some(referrer));
+
+        assert_eq!(current_referrer(), None);
+    }
+
+    #[tokio::test]
+    async fn test_set_content_referral() {
+        let (ops, _temp) = create_test_ops();
+        let content = b"Referred content";
+        let hash = ops
+            .create_content(content, Metadata::new("Referred", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 1_000)
+            .await
+            .unwrap();
+
+        ops.set_content_referral(&hash, Some(500)).unwrap();
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        assert_eq!(manifest.economics.referral, Some(ReferralPolicy::new(500)));
+
+        assert!(ops.set_content_referral(&hash, Some(0)).is_err());
+        assert!(ops.set_content_referral(&hash, Some(5_000)).is_err());
+
+        ops.set_content_referral(&hash, None).unwrap();
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        assert!(manifest.economics.referral.is_none());
+    }
+}
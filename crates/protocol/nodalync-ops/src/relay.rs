@@ -0,0 +1,488 @@
+//! Privacy-preserving queries through relays.
+//!
+//! With a route in the network's [`RelayConfig`], a query for content we
+//! don't hold is wrapped in one sealed layer per relay, onion-style: each
+//! layer is sealed to its relay's key and only names the next hop. The
+//! exit relay queries the provider as if for itself, so the provider never
+//! learns who asked, and no relay sees both the requester and the content.
+//!
+//! The reply travels back the same way, encrypted by each relay with the
+//! reply key from its layer, so only the requester can read it.
+//!
+//! Relays are paid through payment channels: we pay the entry relay its
+//! fee plus everything it must pass on, and each relay pays the next the
+//! same way, with the exit paying the provider. A payment only takes
+//! effect once the reply has come back, so a failed relayed query costs
+//! nothing. Collection bundles are not relayed; only the content itself
+//! comes back.
+
+use nodalync_crypto::{
+    content_hash, decrypt_content, encrypt_content, open_sealed, seal, ContentKey, Hash, PeerId,
+    PublicKey, SealedData,
+};
+use nodalync_net::{RelayConfig, MAX_RELAY_HOPS};
+use nodalync_store::{
+    CacheStore, CachedContent, ChannelStore, LedgerAccount, LedgerEvent, PaymentDirection,
+    PeerStore, StoreError,
+};
+use nodalync_types::Amount;
+use nodalync_valid::{validate_relay_payment, Validator};
+use nodalync_wire::{
+    decode_payload, encode_payload, QueryResponsePayload, RelayHop, RelayLayer, RelayQueryPayload,
+    RelayResponsePayload,
+};
+use tracing::{debug, info, instrument};
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::helpers::verify_content_hash;
+use crate::node_ops::{current_timestamp, lock, NodeOperations};
+use crate::ops::QueryResponse;
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Query content through the relays on `relay.route`.
+    ///
+    /// 1. Seal one layer per relay, from the exit inward, with the amount
+    ///    each relay is owed: its fee plus what it passes on
+    /// 2. Pay the entry relay and send it the outer layer
+    /// 3. Peel each relay's encryption off the reply
+    /// 4. Verify and cache the content, then apply the payment
+    pub(crate) async fn query_via_relays(
+        &self,
+        hash: &Hash,
+        max_price: Amount,
+        relay: &RelayConfig,
+    ) -> OpsResult<QueryResponse> {
+        let timestamp = current_timestamp();
+        if relay.route.len() > MAX_RELAY_HOPS {
+            return Err(OpsError::invalid_operation(format!(
+                "relay route has {} hops, max is {}",
+                relay.route.len(),
+                MAX_RELAY_HOPS
+            )));
+        }
+        let network = self
+            .network()
+            .cloned()
+            .ok_or_else(|| OpsError::invalid_operation("network required to relay queries"))?;
+
+        // 1. Seal the layers, innermost (the exit's) first
+        let mut reply_keys = Vec::with_capacity(relay.route.len());
+        let mut next = RelayHop::Exit {
+            hash: *hash,
+            max_price,
+        };
+        let mut amount = max_price;
+        let mut layer = None;
+        for peer in relay.route.iter().rev() {
+            let public_key = self.relay_public_key(peer)?;
+            let reply_key = ContentKey::generate();
+            let sealed = seal_layer(&public_key, *reply_key.as_bytes(), next)?;
+            amount += relay.route_fee;
+            reply_keys.push(reply_key);
+            next = RelayHop::Relay {
+                peer: *peer,
+                layer: sealed.clone(),
+                amount,
+            };
+            layer = Some(sealed);
+        }
+        reply_keys.reverse();
+        let (entry, layer) = match (relay.route.first(), layer) {
+            (Some(entry), Some(layer)) => (*entry, layer),
+            _ => return Err(OpsError::invalid_operation("relay route is empty")),
+        };
+
+        // 2. Pay the entry relay and send the outer layer
+        let channel = self
+            .state
+            .channels
+            .get(&entry)?
+            .ok_or(OpsError::ChannelRequired)?;
+        if !channel.is_open() {
+            return Err(OpsError::ChannelNotOpen);
+        }
+        if channel.my_balance < amount {
+            return Err(OpsError::InsufficientChannelBalance);
+        }
+        let libp2p_peer = network
+            .libp2p_peer_id(&entry)
+            .ok_or(OpsError::PeerIdNotFound)?;
+        let (payment, payment_nonce) = self.sign_tracked_payment(
+            &entry,
+            &channel,
+            amount,
+            content_hash(&layer.ciphertext),
+            vec![],
+        )?;
+
+        let reply = network
+            .send_relay_query(
+                libp2p_peer,
+                RelayQueryPayload {
+                    layer,
+                    payment: payment.clone(),
+                    payment_nonce,
+                },
+            )
+            .await?;
+
+        // 3. Peel the reply, entry relay's encryption first
+        let mut data = reply.data;
+        for key in &reply_keys {
+            data = decrypt_content(key, &data).map_err(|_| OpsError::DecryptionFailed)?;
+        }
+        let mut response: QueryResponsePayload = decode_payload(&data)
+            .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+
+        // 4. Verify and cache the content, then apply the payment
+        if response.hash != *hash || !verify_content_hash(&response.content, hash) {
+            return Err(OpsError::ContentHashMismatch);
+        }
+        self.check_remote_metadata(&mut response.manifest);
+
+        self.apply_channel_payment(&entry, payment)?;
+        self.record_ledger_transfer(
+            LedgerEvent::RelayPaid,
+            hash,
+            LedgerAccount::Channels,
+            LedgerAccount::QuerySpend,
+            amount,
+        );
+        self.screen_remote_content(&response.manifest)?;
+
+        let cached = CachedContent::new(
+            response.hash,
+            response.content.to_vec(),
+            response.manifest.owner,
+            timestamp,
+            response.payment_receipt.clone(),
+        );
+        self.state.cache.cache(cached)?;
+        self.store_remote_manifest(&response.manifest)?;
+        info!(hash = %hash, hops = relay.route.len(), amount = amount, "Queried content through relays");
+
+        Ok(QueryResponse {
+            content: response.content.into_vec(),
+            manifest: response.manifest,
+            receipt: response.payment_receipt,
+            bundle: Vec::new(),
+        })
+    }
+
+    /// Handle a relayed query, as a relay.
+    ///
+    /// 1. Open our layer of the query
+    /// 2. Validate the payment covers our fee and what we pass on, and
+    ///    record its nonce so it cannot be replayed
+    /// 3. Pass the inner layer to the next relay, or query the content if
+    ///    we are the exit
+    /// 4. Encrypt the reply with our reply key, then credit the payment
+    #[instrument(name = "handle_relay_query", skip_all, fields(peer = %payer))]
+    pub async fn handle_relay_query(
+        &self,
+        payer: &PeerId,
+        request: &RelayQueryPayload,
+    ) -> OpsResult<RelayResponsePayload> {
+        let timestamp = current_timestamp();
+        let network = self
+            .network()
+            .cloned()
+            .ok_or_else(|| OpsError::invalid_operation("network required to relay queries"))?;
+        let config = network.relay_config();
+        if !config.serve {
+            return Err(OpsError::invalid_operation("not relaying queries"));
+        }
+
+        // 1. Open our layer
+        let private_key = self.private_key().ok_or(OpsError::PrivateKeyRequired)?;
+        let data =
+            open_sealed(&request.layer, private_key).map_err(|_| OpsError::DecryptionFailed)?;
+        let layer: RelayLayer = decode_payload(&data)
+            .map_err(|e| OpsError::invalid_operation(format!("decode error: {}", e)))?;
+        let forward = match &layer.next {
+            RelayHop::Relay { amount, .. } => *amount,
+            RelayHop::Exit { max_price, .. } => *max_price,
+        };
+
+        // 2. Validate the payment
+        let layer_hash = content_hash(&request.layer.ciphertext);
+        let channel = self
+            .state
+            .channels
+            .get(payer)?
+            .ok_or(OpsError::ChannelRequired)?;
+        let payer_pubkey = self
+            .state
+            .peers
+            .get(payer)
+            .ok()
+            .flatten()
+            .map(|info| info.public_key)
+            .filter(|pk| pk.0 != [0u8; 32]);
+        let last_nonce = self
+            .state
+            .channels
+            .payment_nonces(&channel.channel_id)?
+            .map(|nonces| nonces.last_received);
+
+        validate_relay_payment(
+            &request.payment,
+            config.fee + forward,
+            &self.peer_id(),
+            &layer_hash,
+            &channel,
+            payer_pubkey.as_ref(),
+            request.payment_nonce,
+            last_nonce,
+        )
+        .map_err(|e| OpsError::PaymentValidationFailed(e.to_string()))?;
+
+        self.state
+            .channels
+            .record_payment_nonce(
+                &channel.channel_id,
+                payer,
+                PaymentDirection::Received,
+                request.payment_nonce,
+                timestamp,
+            )
+            .map_err(|e| match e {
+                StoreError::StaleNonce { .. } => OpsError::PaymentValidationFailed(e.to_string()),
+                e => e.into(),
+            })?;
+
+        // 3. Pass the query on
+        let reply = match layer.next {
+            RelayHop::Relay {
+                peer,
+                layer: inner,
+                amount,
+            } => {
+                debug!(next = %peer, amount = amount, "Passing relayed query on");
+                self.forward_relay_query(&peer, inner, amount).await?
+            }
+            RelayHop::Exit { hash, max_price } => {
+                debug!(hash = %hash, "Querying content as exit relay");
+                let response = self.query_content_direct(&hash, max_price, None).await?;
+                encode_payload(&QueryResponsePayload {
+                    hash,
+                    content: response.content.into(),
+                    manifest: response.manifest,
+                    payment_receipt: response.receipt,
+                    content_key: None,
+                    bundle: Vec::new(),
+                    delivery: None,
+                    section: None,
+                    unit: None,
+                    trial_reads_left: None,
+                })
+                .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?
+            }
+        };
+
+        // 4. Encrypt the reply and credit the payment
+        let data = encrypt_content(&ContentKey::from_bytes(layer.reply_key), &reply);
+
+        // The channel may have moved on while the query was passed along
+        let channel_lock = self.channel_locks.for_peer(payer);
+        let _guard = lock(&channel_lock);
+        let mut channel = self
+            .state
+            .channels
+            .get(payer)?
+            .ok_or(OpsError::ChannelRequired)?;
+        let payment = request.payment.clone();
+        channel
+            .receive(payment.clone(), timestamp)
+            .map_err(|_| OpsError::InsufficientChannelBalance)?;
+        channel.nonce = channel.nonce.max(request.payment_nonce);
+        self.commit_channel_state(payer, &channel)?;
+        self.state.channels.add_payment(payer, payment.clone())?;
+        self.record_ledger_transfer(
+            LedgerEvent::RelayReceived,
+            layer_hash,
+            LedgerAccount::Revenue,
+            LedgerAccount::Channels,
+            payment.amount,
+        );
+        info!(payer = %payer, amount = payment.amount, "Relayed query");
+
+        Ok(RelayResponsePayload { data })
+    }
+
+    /// Pay the next relay and send it its layer, returning the reply as
+    /// it came back.
+    async fn forward_relay_query(
+        &self,
+        peer: &PeerId,
+        layer: SealedData,
+        amount: Amount,
+    ) -> OpsResult<Vec<u8>> {
+        let network = self
+            .network()
+            .cloned()
+            .ok_or_else(|| OpsError::invalid_operation("network required to relay queries"))?;
+        let channel = self
+            .state
+            .channels
+            .get(peer)?
+            .ok_or(OpsError::ChannelRequired)?;
+        if !channel.is_open() {
+            return Err(OpsError::ChannelNotOpen);
+        }
+        if channel.my_balance < amount {
+            return Err(OpsError::InsufficientChannelBalance);
+        }
+        let libp2p_peer = network
+            .libp2p_peer_id(peer)
+            .ok_or(OpsError::PeerIdNotFound)?;
+
+        let layer_hash = content_hash(&layer.ciphertext);
+        let (payment, payment_nonce) =
+            self.sign_tracked_payment(peer, &channel, amount, layer_hash, vec![])?;
+        let reply = network
+            .send_relay_query(
+                libp2p_peer,
+                RelayQueryPayload {
+                    layer,
+                    payment: payment.clone(),
+                    payment_nonce,
+                },
+            )
+            .await?;
+
+        self.apply_channel_payment(peer, payment)?;
+        self.record_ledger_transfer(
+            LedgerEvent::RelayPaid,
+            layer_hash,
+            LedgerAccount::Channels,
+            LedgerAccount::QuerySpend,
+            amount,
+        );
+        Ok(reply.data)
+    }
+
+    /// Get a relay's identity key, which its layer is sealed to.
+    fn relay_public_key(&self, relay: &PeerId) -> OpsResult<PublicKey> {
+        self.state
+            .peers
+            .get(relay)?
+            .map(|info| info.public_key)
+            .filter(|pk| pk.0 != [0u8; 32])
+            .ok_or_else(|| {
+                OpsError::invalid_operation(format!("no public key for relay {}", relay))
+            })
+    }
+}
+
+/// Encode a relay layer and seal it to the relay's key.
+fn seal_layer(relay: &PublicKey, reply_key: [u8; 32], next: RelayHop) -> OpsResult<SealedData> {
+    let layer = encode_payload(&RelayLayer { reply_key, next })
+        .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?;
+    seal(relay, &layer).map_err(|e| OpsError::invalid_operation(format!("sealing failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key, PrivateKey};
+    use nodalync_net::Network;
+    use nodalync_store::{NodeState, NodeStateConfig, PeerInfo};
+    use nodalync_test_utils::MockNetwork;
+    use nodalync_types::Channel;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops =
+            DefaultNodeOperations::with_defaults(state, peer_id_from_public_key(&public_key));
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    /// A relay known to `ops`, returning its key.
+    fn add_relay(ops: &mut DefaultNodeOperations) -> (PeerId, PrivateKey) {
+        let (private_key, public_key) = generate_identity();
+        let peer = peer_id_from_public_key(&public_key);
+        let info = PeerInfo::new(peer, public_key, Vec::new(), current_timestamp());
+        ops.state.peers.upsert(&info).unwrap();
+        (peer, private_key)
+    }
+
+    fn open_layer(layer: &SealedData, key: &PrivateKey) -> RelayLayer {
+        decode_payload(&open_sealed(layer, key).unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_query_via_relays_seals_route() {
+        let (mut ops, _temp) = create_test_ops();
+        let (entry, entry_key) = add_relay(&mut ops);
+        let (exit, exit_key) = add_relay(&mut ops);
+        let mut channel = Channel::new(content_hash(b"channel"), entry, 1_000, 1_000);
+        channel.mark_open(1_000, 1_000);
+        ops.state.channels.create(&entry, channel).unwrap();
+
+        let network = MockNetwork::new();
+        network.register_peer_mapping(nodalync_net::PeerId::random(), entry);
+        ops.set_network(Arc::new(network.clone()));
+
+        let hash = content_hash(b"wanted");
+        let relay = RelayConfig::default().with_route(vec![entry, exit], 10);
+        assert!(ops.query_via_relays(&hash, 100, &relay).await.is_err());
+
+        // The entry relay is paid for both hops and only learns the exit
+        let sent = network.relay_queries();
+        assert_eq!(sent.len(), 1);
+        let request = &sent[0].1;
+        assert_eq!(request.payment.amount, 120);
+        assert_eq!(request.payment.recipient, entry);
+        assert_eq!(
+            request.payment.query_hash,
+            content_hash(&request.layer.ciphertext)
+        );
+        let RelayHop::Relay {
+            peer,
+            layer,
+            amount,
+        } = open_layer(&request.layer, &entry_key).next
+        else {
+            panic!("entry relay should pass the query on");
+        };
+        assert_eq!((peer, amount), (exit, 110));
+        assert!(open_sealed(&layer, &entry_key).is_err());
+
+        // Only the exit learns the content
+        assert_eq!(
+            open_layer(&layer, &exit_key).next,
+            RelayHop::Exit {
+                hash,
+                max_price: 100
+            }
+        );
+
+        // No reply, so nothing was paid
+        let channel = ops.state.channels.get(&entry).unwrap().unwrap();
+        assert_eq!(channel.my_balance, 1_000);
+    }
+
+    #[tokio::test]
+    async fn test_relay_route_requires_known_keys() {
+        let (mut ops, _temp) = create_test_ops();
+        ops.set_network(Arc::new(MockNetwork::new()));
+        let relay = RelayConfig::default().with_route(vec![PeerId([9u8; 20])], 10);
+        assert!(matches!(
+            ops.query_via_relays(&content_hash(b"wanted"), 100, &relay)
+                .await,
+            Err(OpsError::InvalidOperation(_))
+        ));
+    }
+}
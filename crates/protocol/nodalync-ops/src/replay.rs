@@ -0,0 +1,143 @@
+//! Replaying recorded wire messages.
+//!
+//! A node with a replay log records every wire message it sends and
+//! receives (see [`nodalync_net::replay`]). [`replay_entry`](NodeOperations::replay_entry)
+//! feeds a recorded inbound message back through the handlers, with the
+//! clock pinned to the moment it was received so timestamp checks come out
+//! as they did. Run against fresh in-memory state, this reproduces how the
+//! node reacted to a sequence of messages without a network.
+
+use nodalync_net::{NetworkEvent, ReplayDirection, ReplayEntry, ReplayKind};
+use nodalync_valid::Validator;
+use nodalync_wire::MessageType;
+
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, lock, NodeOperations};
+
+/// What replaying one entry did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayOutcome {
+    /// An inbound request that the handlers answered.
+    Responded(MessageType),
+    /// An inbound request that the handlers dropped without answering.
+    NoResponse,
+    /// An inbound broadcast, handled.
+    Handled,
+    /// Not an inbound request or broadcast; only shown.
+    Skipped,
+    /// The handlers returned an error.
+    Failed(String),
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Feed a recorded inbound request or broadcast back through the
+    /// handlers, as if it had just arrived.
+    ///
+    /// Pins the clock offset so that [`network_time`](Self::network_time)
+    /// reads the time the entry was recorded, corrected by the offset in
+    /// force then, and leaves it pinned; use a throwaway instance. Requests
+    /// without a recorded peer are attributed to a random one.
+    pub async fn replay_entry(&self, entry: &ReplayEntry) -> ReplayOutcome {
+        if entry.direction != ReplayDirection::Inbound {
+            return ReplayOutcome::Skipped;
+        }
+        let recorded = entry.timestamp.saturating_add_signed(entry.clock_offset_ms);
+        lock(&self.clock_skew).pin(recorded as i64 - current_timestamp() as i64);
+
+        match entry.kind {
+            ReplayKind::Request => {
+                let peer = entry.peer_id().unwrap_or_else(nodalync_net::PeerId::random);
+                match self.handle_inbound_request(&peer, &entry.data).await {
+                    Ok(Some((message_type, _))) => ReplayOutcome::Responded(message_type),
+                    Ok(None) => ReplayOutcome::NoResponse,
+                    Err(e) => ReplayOutcome::Failed(e.to_string()),
+                }
+            }
+            ReplayKind::Broadcast => {
+                let event = NetworkEvent::BroadcastReceived {
+                    topic: entry.topic.clone().unwrap_or_default(),
+                    data: entry.data.clone(),
+                };
+                match self.handle_network_event(event).await {
+                    Ok(_) => ReplayOutcome::Handled,
+                    Err(e) => ReplayOutcome::Failed(e.to_string()),
+                }
+            }
+            ReplayKind::Response => ReplayOutcome::Skipped,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::NodeState;
+    use nodalync_types::{Metadata, Visibility};
+    use nodalync_wire::{create_message, encode_message, encode_payload, PreviewRequestPayload};
+
+    #[tokio::test]
+    async fn test_replay_entry() {
+        let state = NodeState::open_in_memory().unwrap();
+        let (_, public_key) = generate_identity();
+        let ops = DefaultNodeOperations::with_defaults(state, peer_id_from_public_key(&public_key));
+        let content = b"Replayed notes";
+        let hash = ops
+            .create_content(content, Metadata::new("Notes", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 0)
+            .await
+            .unwrap();
+
+        // A preview request received a day ago: stale by today's clock, but
+        // on time when replayed at the moment it was received
+        let received = current_timestamp() - 86_400_000;
+        let (private_key, public_key) = generate_identity();
+        let data = encode_message(&create_message(
+            MessageType::PreviewRequest,
+            encode_payload(&PreviewRequestPayload { hash }).unwrap(),
+            peer_id_from_public_key(&public_key),
+            received - 50,
+            &private_key,
+        ))
+        .unwrap();
+        let request = ReplayEntry::new(
+            ReplayDirection::Inbound,
+            ReplayKind::Request,
+            &data,
+            received,
+        )
+        .with_peer(nodalync_net::PeerId::random());
+        assert_eq!(
+            ops.replay_entry(&request).await,
+            ReplayOutcome::Responded(MessageType::PreviewResponse)
+        );
+
+        // Off by more than the allowed skew, it's dropped
+        let skewed = request.clone().with_clock_offset(-600_000);
+        assert_eq!(ops.replay_entry(&skewed).await, ReplayOutcome::NoResponse);
+
+        // Undecodable broadcasts are handled (and ignored); outbound
+        // messages are skipped
+        let broadcast = ReplayEntry::new(
+            ReplayDirection::Inbound,
+            ReplayKind::Broadcast,
+            b"junk",
+            received,
+        )
+        .with_topic("/nodalync/announce/1.0.0");
+        assert_eq!(ops.replay_entry(&broadcast).await, ReplayOutcome::Handled);
+        let outbound = ReplayEntry::new(
+            ReplayDirection::Outbound,
+            ReplayKind::Request,
+            &data,
+            received,
+        );
+        assert_eq!(ops.replay_entry(&outbound).await, ReplayOutcome::Skipped);
+    }
+}
@@ -0,0 +1,404 @@
+//! Read-only replicas of a publisher's catalog.
+//!
+//! A publisher (the primary) can run several serving nodes. It signs a
+//! [`ReplicaDelegation`] naming a replica and exports its published
+//! content as a [`ReplicaCatalog`], moved to the replica as a file. The
+//! replica imports the catalog, serves previews and queries for it, and
+//! announces it with the delegation attached so peers attribute it to the
+//! primary.
+//!
+//! Replicated content stays owned by the primary: the replica can't
+//! update, publish or unpublish it, and queries for it are distributed to
+//! the primary as owner. The delegation names the primary's settlement
+//! account so the replica settles to it.
+
+use std::collections::HashSet;
+
+use nodalync_crypto::{PeerId, Signature, Timestamp};
+use nodalync_store::{ContentStore, ManifestFilter, ManifestStore, ReplicaStore};
+use nodalync_types::{ContentType, ReplicaDelegation, Visibility};
+use nodalync_valid::{
+    sign_replica_catalog, sign_replica_delegation, validate_replica_catalog, Validator,
+};
+use nodalync_wire::{ReplicaCatalog, ReplicaItem};
+use tracing::{debug, info, warn};
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+/// What importing a replica catalog did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplicaImport {
+    /// The primary whose catalog was imported.
+    pub primary: PeerId,
+    /// When the delegation expires.
+    pub expires_at: Timestamp,
+    /// Manifests we didn't have.
+    pub manifests_added: usize,
+    /// Manifests that changed since the last import.
+    pub manifests_updated: usize,
+    /// Manifests no longer in the catalog, no longer served.
+    pub manifests_withdrawn: usize,
+    /// Whether the primary's settlement account was registered.
+    pub account_registered: bool,
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Sign a delegation letting `replica` serve our catalog until
+    /// `expires_at`.
+    ///
+    /// Names our settlement account, if we have one.
+    pub fn delegate_replica(
+        &self,
+        replica: &PeerId,
+        expires_at: Timestamp,
+    ) -> OpsResult<ReplicaDelegation> {
+        let private_key = self.private_key().ok_or(OpsError::PrivateKeyRequired)?;
+        if *replica == self.peer_id() {
+            return Err(OpsError::invalid_operation("cannot delegate to ourselves"));
+        }
+        let now = current_timestamp();
+        if expires_at <= now {
+            return Err(OpsError::invalid_operation(
+                "delegation must expire in the future",
+            ));
+        }
+
+        let mut delegation = ReplicaDelegation::new(
+            self.peer_id(),
+            private_key.public_key(),
+            *replica,
+            self.settlement().map(|s| s.get_own_account_string()),
+            now,
+            expires_at,
+        );
+        sign_replica_delegation(private_key, &mut delegation);
+        Ok(delegation)
+    }
+
+    /// Export our published content for `replica` to serve until
+    /// `expires_at`.
+    ///
+    /// Includes every shared or unlisted manifest we own, with its
+    /// content. L2 content is never published, so never exported.
+    pub fn export_replica_catalog(
+        &self,
+        replica: &PeerId,
+        expires_at: Timestamp,
+    ) -> OpsResult<ReplicaCatalog> {
+        let delegation = self.delegate_replica(replica, expires_at)?;
+        let private_key = self.private_key().ok_or(OpsError::PrivateKeyRequired)?;
+
+        let filter = ManifestFilter::new().with_owner(self.peer_id());
+        let mut items = Vec::new();
+        for manifest in self.state.manifests.list(filter)? {
+            if !matches!(
+                manifest.visibility,
+                Visibility::Shared | Visibility::Unlisted
+            ) || manifest.content_type == ContentType::L2
+            {
+                continue;
+            }
+            let Some(content) = self.state.content.load(&manifest.hash)? else {
+                warn!(hash = %manifest.hash, "Published content missing, not exported");
+                continue;
+            };
+            items.push(ReplicaItem { manifest, content });
+        }
+
+        let mut catalog = ReplicaCatalog {
+            delegation,
+            created_at: current_timestamp(),
+            items,
+            signature: Signature::from_bytes([0u8; 64]),
+        };
+        sign_replica_catalog(private_key, &mut catalog)?;
+
+        info!(replica = %replica, items = catalog.items.len(), "Exported replica catalog");
+        Ok(catalog)
+    }
+
+    /// Import a primary's catalog to serve it.
+    ///
+    /// The catalog must be signed by the primary, under a delegation
+    /// naming us that hasn't expired, and no older than the delegation
+    /// already held from the primary. Manifests and content are stored as
+    /// the primary's; content we held from an earlier import that is no
+    /// longer in the catalog is made private so it is no longer served.
+    pub fn import_replica_catalog(&self, catalog: &ReplicaCatalog) -> OpsResult<ReplicaImport> {
+        let now = current_timestamp();
+        validate_replica_catalog(catalog, now)?;
+
+        let delegation = &catalog.delegation;
+        if delegation.replica != self.peer_id() {
+            return Err(OpsError::invalid_operation(format!(
+                "catalog is delegated to {}, not us",
+                delegation.replica
+            )));
+        }
+        if let Some(held) = self.state.replicas.get_delegation(&delegation.primary)? {
+            if held.issued_at > delegation.issued_at {
+                return Err(OpsError::invalid_operation(
+                    "catalog is older than the one already imported",
+                ));
+            }
+        }
+
+        let mut import = ReplicaImport {
+            primary: delegation.primary,
+            expires_at: delegation.expires_at,
+            manifests_added: 0,
+            manifests_updated: 0,
+            manifests_withdrawn: 0,
+            account_registered: false,
+        };
+
+        let mut served = HashSet::new();
+        for item in &catalog.items {
+            served.insert(item.manifest.hash);
+            let held = self.state.manifests.load(&item.manifest.hash)?;
+            if held.as_ref() == Some(&item.manifest) {
+                continue;
+            }
+            self.state.content.store(&item.content)?;
+            if held.is_some() {
+                self.state.manifests.update(&item.manifest)?;
+                import.manifests_updated += 1;
+            } else {
+                self.store_remote_manifest(&item.manifest)?;
+                import.manifests_added += 1;
+            }
+        }
+
+        let filter = ManifestFilter::new().with_owner(delegation.primary);
+        for mut manifest in self.state.manifests.list(filter)? {
+            // Only content we serve: manifests merely seen in previews
+            // and query responses have no local content
+            if served.contains(&manifest.hash)
+                || !self.state.content.exists(&manifest.hash)
+                || !matches!(
+                    manifest.visibility,
+                    Visibility::Shared | Visibility::Unlisted
+                )
+            {
+                continue;
+            }
+            manifest.visibility = Visibility::Private;
+            self.state.manifests.update(&manifest)?;
+            import.manifests_withdrawn += 1;
+        }
+
+        self.state.replicas.set_delegation(delegation)?;
+
+        if let (Some(settlement), Some(account)) =
+            (self.settlement(), &delegation.settlement_account)
+        {
+            match nodalync_settle::AccountId::from_string(account) {
+                Ok(account_id) => {
+                    settlement.register_peer_account(&delegation.primary, account_id);
+                    import.account_registered = true;
+                }
+                Err(e) => {
+                    debug!(primary = %delegation.primary, error = %e, "Skipped settlement account")
+                }
+            }
+        }
+
+        info!(
+            primary = %import.primary,
+            added = import.manifests_added,
+            updated = import.manifests_updated,
+            withdrawn = import.manifests_withdrawn,
+            "Imported replica catalog"
+        );
+        Ok(import)
+    }
+
+    /// Delegations held from primaries whose catalogs we serve, soonest to
+    /// expire first.
+    pub fn list_replica_delegations(&self) -> OpsResult<Vec<ReplicaDelegation>> {
+        Ok(self.state.replicas.list_delegations()?)
+    }
+
+    /// Announce the shared content of every primary we replicate.
+    ///
+    /// Each announcement carries the primary's delegation and is signed by
+    /// us. Expired delegations are skipped. Returns the number of
+    /// manifests announced; without a network nothing is announced.
+    pub async fn announce_replicated_content(&self) -> OpsResult<usize> {
+        if self.network().is_none() {
+            return Ok(0);
+        }
+
+        let now = current_timestamp();
+        let mut announced = 0;
+        for delegation in self.state.replicas.list_delegations()? {
+            if delegation.is_expired(now) {
+                debug!(primary = %delegation.primary, "Replica delegation expired, not announcing");
+                continue;
+            }
+            let filter = ManifestFilter::new()
+                .with_owner(delegation.primary)
+                .with_visibility(Visibility::Shared);
+            for manifest in self.state.manifests.list(filter)? {
+                let l1_summary = match self.preview_summary(&manifest.hash) {
+                    Ok(summary) => summary,
+                    Err(e) => {
+                        warn!(hash = %manifest.hash, error = %e, "Skipped announcing replicated content");
+                        continue;
+                    }
+                };
+                self.announce_manifest_with(&manifest, l1_summary, Some(delegation.clone()))
+                    .await;
+                announced += 1;
+            }
+        }
+
+        if announced > 0 {
+            info!(announced, "Announced replicated content");
+        }
+        Ok(announced)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OpsConfig;
+    use crate::{DefaultNodeOperations, Network};
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::NodeState;
+    use nodalync_test_utils::MockNetwork;
+    use nodalync_types::Metadata;
+    use nodalync_valid::validate_announcement;
+    use std::sync::Arc;
+
+    fn create_test_ops() -> DefaultNodeOperations {
+        let state = NodeState::open_in_memory().unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops = DefaultNodeOperations::with_config(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default(),
+        );
+        ops.set_private_key(private_key);
+        ops
+    }
+
+    fn expires_at() -> Timestamp {
+        current_timestamp() + 86_400_000
+    }
+
+    async fn publish(ops: &DefaultNodeOperations, content: &[u8], visibility: Visibility) {
+        let meta = Metadata::new("Replicated", content.len() as u64);
+        let hash = ops.create_content(content, meta).unwrap();
+        ops.publish_content(&hash, visibility, 10).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_export_and_import_catalog() {
+        let primary = create_test_ops();
+        publish(&primary, b"Shared notes", Visibility::Shared).await;
+        publish(&primary, b"Unlisted notes", Visibility::Unlisted).await;
+        let private = b"Private notes";
+        primary
+            .create_content(private, Metadata::new("Private", private.len() as u64))
+            .unwrap();
+
+        let replica = create_test_ops();
+        let catalog = primary
+            .export_replica_catalog(&replica.peer_id(), expires_at())
+            .unwrap();
+        assert_eq!(catalog.items.len(), 2);
+
+        let import = replica.import_replica_catalog(&catalog).unwrap();
+        assert_eq!(import.primary, primary.peer_id());
+        assert_eq!(import.manifests_added, 2);
+        assert_eq!(
+            replica.list_replica_delegations().unwrap(),
+            vec![catalog.delegation.clone()]
+        );
+
+        // The replica holds the content but doesn't own it
+        let hash = catalog.items[0].manifest.hash;
+        assert!(replica.state.content.load(&hash).unwrap().is_some());
+        assert!(matches!(
+            replica.publish_content(&hash, Visibility::Shared, 0).await,
+            Err(OpsError::AccessDenied)
+        ));
+
+        // Importing again changes nothing
+        let import = replica.import_replica_catalog(&catalog).unwrap();
+        assert_eq!(import.manifests_added + import.manifests_updated, 0);
+
+        // Nobody else can import it
+        let other = create_test_ops();
+        assert!(other.import_replica_catalog(&catalog).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_withdraws_dropped_content() {
+        let primary = create_test_ops();
+        publish(&primary, b"Kept", Visibility::Shared).await;
+        publish(&primary, b"Dropped", Visibility::Shared).await;
+
+        let replica = create_test_ops();
+        let first = primary
+            .export_replica_catalog(&replica.peer_id(), expires_at())
+            .unwrap();
+        replica.import_replica_catalog(&first).unwrap();
+
+        let dropped = nodalync_crypto::content_hash(b"Dropped");
+        primary.unpublish_content(&dropped).await.unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = primary
+            .export_replica_catalog(&replica.peer_id(), expires_at())
+            .unwrap();
+        let import = replica.import_replica_catalog(&second).unwrap();
+        assert_eq!(import.manifests_withdrawn, 1);
+        let manifest = replica.state.manifests.load(&dropped).unwrap().unwrap();
+        assert_eq!(manifest.visibility, Visibility::Private);
+
+        // The older catalog can't be replayed over the newer one
+        assert!(replica.import_replica_catalog(&first).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_announce_replicated_content() {
+        let primary = create_test_ops();
+        publish(&primary, b"Shared notes", Visibility::Shared).await;
+
+        let network = Arc::new(MockNetwork::new());
+        let mut replica = create_test_ops();
+        replica.set_network(Arc::clone(&network) as Arc<dyn Network>);
+        let catalog = primary
+            .export_replica_catalog(&replica.peer_id(), expires_at())
+            .unwrap();
+        replica.import_replica_catalog(&catalog).unwrap();
+
+        assert_eq!(replica.announce_replicated_content().await.unwrap(), 1);
+        let hash = catalog.items[0].manifest.hash;
+        let announcement = network.dht_entries().remove(&hash).unwrap();
+        assert_eq!(
+            validate_announcement(&announcement, current_timestamp()).unwrap(),
+            Some(primary.peer_id())
+        );
+        assert!(replica.announcement_acceptable(&announcement));
+    }
+
+    #[test]
+    fn test_delegate_replica_rejects_bad_terms() {
+        let primary = create_test_ops();
+        assert!(primary
+            .delegate_replica(&primary.peer_id(), expires_at())
+            .is_err());
+
+        let replica = create_test_ops();
+        assert!(primary.delegate_replica(&replica.peer_id(), 1).is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! Data retention.
+//!
+//! Announcements, cached content, peer records, channel history and access
+//! analytics would otherwise be kept forever. `RetentionConfig` gives each
+//! category a time-to-live; `enforce_retention` deletes what has outlived
+//! it, and the node calls it every `check_interval_secs`. Peers and
+//! channels that are still in use are never deleted.
+
+use nodalync_crypto::Timestamp;
+use nodalync_store::{RetentionCategory, RetentionStats};
+use nodalync_valid::Validator;
+use tracing::info;
+
+use crate::error::OpsResult;
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+/// What the retention policy holds for one category.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionStatus {
+    /// The category.
+    pub category: RetentionCategory,
+    /// Configured TTL in seconds (`None` = kept forever).
+    pub ttl_secs: Option<u64>,
+    /// Records held, and those past the TTL.
+    pub stats: RetentionStats,
+}
+
+/// Records deleted from one category by `enforce_retention`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPurge {
+    /// The category.
+    pub category: RetentionCategory,
+    /// Number of records deleted.
+    pub deleted: u64,
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Report what is held in each retention category, and how much of it
+    /// is past its TTL.
+    ///
+    /// Categories without a TTL report nothing as expired.
+    pub fn retention_status(&self) -> OpsResult<Vec<RetentionStatus>> {
+        let now = current_timestamp();
+        RetentionCategory::ALL
+            .into_iter()
+            .map(|category| {
+                let ttl_secs = self.config.retention.ttl(category);
+                let stats = self
+                    .state
+                    .retention_stats(category, expiry_cutoff(now, ttl_secs))?;
+                Ok(RetentionStatus {
+                    category,
+                    ttl_secs,
+                    stats,
+                })
+            })
+            .collect()
+    }
+
+    /// Delete records that have outlived their category's TTL.
+    ///
+    /// Returns what was deleted from each category with a TTL.
+    pub fn enforce_retention(&self) -> OpsResult<Vec<RetentionPurge>> {
+        let now = current_timestamp();
+        let mut purges = Vec::new();
+        for category in RetentionCategory::ALL {
+            let Some(ttl_secs) = self.config.retention.ttl(category) else {
+                continue;
+            };
+            let deleted = self
+                .state
+                .purge_expired(category, expiry_cutoff(now, Some(ttl_secs)))?;
+            if deleted > 0 {
+                info!(category = %category, deleted, "Expired data by retention policy");
+            }
+            purges.push(RetentionPurge { category, deleted });
+        }
+        Ok(purges)
+    }
+}
+
+/// Records last touched before this are past the TTL (ms). Without a TTL
+/// nothing is.
+fn expiry_cutoff(now: Timestamp, ttl_secs: Option<u64>) -> Timestamp {
+    ttl_secs.map_or(0, |ttl| now.saturating_sub(ttl.saturating_mul(1000)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{OpsConfig, RetentionConfig};
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_store::{
+        AccessKind, AccessLogStore, AccessRecord, AccessRequester, NodeState, NodeStateConfig,
+        PeerInfo, PeerStore,
+    };
+    use tempfile::TempDir;
+
+    fn create_test_ops(retention: RetentionConfig) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (_, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+        let config = OpsConfig::default().with_retention(retention);
+        let ops = DefaultNodeOperations::with_config(state, peer_id, config);
+        (ops, temp_dir)
+    }
+
+    #[test]
+    fn test_enforce_retention() {
+        let day = 86_400;
+        let (ops, _temp) = create_test_ops(
+            RetentionConfig::default().with_ttl(RetentionCategory::Analytics, Some(day)),
+        );
+        let now = current_timestamp();
+
+        // Two-day-old and fresh access records and peers
+        let (_, public_key) = generate_identity();
+        let peer = peer_id_from_public_key(&public_key);
+        for timestamp in [now - 2 * day * 1000, now] {
+            ops.state
+                .access_log
+                .record(&AccessRecord::new(
+                    content_hash(b"content"),
+                    AccessRequester::Peer(peer),
+                    AccessKind::Query,
+                    0,
+                    timestamp,
+                ))
+                .unwrap();
+        }
+        ops.state
+            .peers
+            .upsert(&PeerInfo::new(peer, public_key, vec![], 0))
+            .unwrap();
+
+        let status = ops.retention_status().unwrap();
+        assert_eq!(status.len(), RetentionCategory::ALL.len());
+        let analytics = status
+            .iter()
+            .find(|s| s.category == RetentionCategory::Analytics)
+            .unwrap();
+        assert_eq!(analytics.ttl_secs, Some(day));
+        assert_eq!((analytics.stats.records, analytics.stats.expired), (2, 1));
+        // Without a TTL nothing is expired, however old
+        let peers = status
+            .iter()
+            .find(|s| s.category == RetentionCategory::PeerRecords)
+            .unwrap();
+        assert_eq!(peers.ttl_secs, None);
+        assert_eq!((peers.stats.records, peers.stats.expired), (1, 0));
+
+        // Only categories with a TTL are purged
+        assert_eq!(
+            ops.enforce_retention().unwrap(),
+            vec![RetentionPurge {
+                category: RetentionCategory::Analytics,
+                deleted: 1,
+            }]
+        );
+        assert_eq!(
+            ops.state
+                .access_log
+                .for_content(&content_hash(b"content"))
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(ops.state.peers.get(&peer).unwrap().is_some());
+    }
+}
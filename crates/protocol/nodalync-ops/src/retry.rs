@@ -0,0 +1,446 @@
+//! Structured query errors and retries.
+//!
+//! A query we can't serve is answered with a QUERY_ERROR built from the
+//! [`OpsError`]: its code and message, how long to wait before retrying
+//! when the error is temporary, and other providers that may hold the
+//! content when we can't serve it. Queries beyond
+//! [`OpsConfig::query_limit`](crate::OpsConfig::query_limit) are refused
+//! as busy.
+//!
+//! On the requesting side, a provider's retry delay is waited out, within
+//! [`OpsConfig::query_retry`](crate::OpsConfig::query_retry), before asking
+//! it again, and the alternative providers it names are tried when it
+//! can't serve the content.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use nodalync_crypto::{Hash, UNKNOWN_PEER_ID};
+use nodalync_net::{Network, NetworkError, NetworkResult};
+use nodalync_store::ManifestStore;
+use nodalync_types::Amount;
+use nodalync_valid::Validator;
+use nodalync_wire::{
+    QueryErrorPayload, QueryRequestPayload, QueryResponsePayload, MAX_ALTERNATIVE_PROVIDERS,
+};
+use tracing::debug;
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::NodeOperations;
+use crate::ops::QueryResponse;
+
+/// Count of queries being served.
+#[derive(Debug, Default)]
+pub(crate) struct QuerySlots {
+    in_flight: AtomicUsize,
+}
+
+/// A query being served, releasing its slot when dropped.
+pub(crate) struct QuerySlot<'a>(&'a AtomicUsize);
+
+impl Drop for QuerySlot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl QuerySlots {
+    /// Take a slot, unless `limit` queries are already being served.
+    fn acquire(&self, limit: usize) -> Option<QuerySlot<'_>> {
+        self.in_flight
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                (n < limit).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| QuerySlot(&self.in_flight))
+    }
+}
+
+/// Whether a provider's error names alternative providers to try.
+pub(crate) fn names_alternatives(error: &NetworkError) -> bool {
+    matches!(
+        error,
+        NetworkError::QueryError { alternative_providers, .. } if !alternative_providers.is_empty()
+    )
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Queries being served right now.
+    pub fn queries_in_flight(&self) -> usize {
+        self.query_slots.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Take a slot for serving a query, or refuse it as
+    /// [`OpsError::Busy`].
+    pub(crate) fn acquire_query_slot(&self) -> OpsResult<QuerySlot<'_>> {
+        let limit = &self.config.query_limit;
+        self.query_slots
+            .acquire(limit.max_concurrent)
+            .ok_or(OpsError::Busy {
+                retry_after_ms: limit.retry_after_ms,
+            })
+    }
+
+    /// The QUERY_ERROR answering a request for `hash` from `requester`
+    /// that failed with `error`.
+    ///
+    /// Besides the code and message, it carries our peer IDs when a
+    /// channel is required, the challenge to answer, the retry delay of
+    /// temporary errors, and, when another provider may succeed, the
+    /// providers we know of other than us and the requester.
+    pub(crate) fn query_error_payload(
+        &self,
+        hash: Hash,
+        requester: &nodalync_net::PeerId,
+        error: &OpsError,
+    ) -> QueryErrorPayload {
+        let mut payload = QueryErrorPayload::new(hash, error.error_code(), error.to_string());
+        match error {
+            OpsError::ChannelRequired => {
+                payload.message = Some("Payment channel required for paid content".to_string());
+                payload.required_channel_peer_id = Some(self.peer_id());
+                payload.required_channel_libp2p_peer =
+                    self.network().map(|n| n.local_peer_id().to_string());
+            }
+            OpsError::ChallengeRequired(challenge) => payload.challenge = Some(challenge.clone()),
+            _ => {}
+        }
+        payload.retry_after_ms = error.retry_after_ms();
+        if error.suggests_alternatives() {
+            payload.alternative_providers = self.alternative_providers(&hash, requester);
+        }
+        payload
+    }
+
+    /// Providers other than us and `requester` known to hold content.
+    fn alternative_providers(&self, hash: &Hash, requester: &nodalync_net::PeerId) -> Vec<String> {
+        let Some(network) = self.network() else {
+            return Vec::new();
+        };
+        let owner = match self.state.manifests.load(hash) {
+            Ok(Some(manifest)) => manifest.owner,
+            _ => UNKNOWN_PEER_ID,
+        };
+        self.content_providers(network, hash, &owner)
+            .into_iter()
+            .filter(|provider| provider != requester)
+            .take(MAX_ALTERNATIVE_PROVIDERS)
+            .map(|provider| provider.to_string())
+            .collect()
+    }
+
+    /// Send a query, asking again after the provider's retry delay when it
+    /// refuses with one.
+    ///
+    /// Gives up after `max_retries` retries, or at once if the delay is
+    /// longer than `max_wait_ms`.
+    pub(crate) async fn send_query_retrying(
+        &self,
+        network: &Arc<dyn Network>,
+        peer: nodalync_net::PeerId,
+        request: QueryRequestPayload,
+    ) -> NetworkResult<QueryResponsePayload> {
+        let retry = &self.config.query_retry;
+        let mut retries = 0;
+        loop {
+            match network.send_query(peer, request.clone()).await {
+                Err(NetworkError::QueryError {
+                    retry_after_ms: Some(wait),
+                    ..
+                }) if retries < retry.max_retries && wait <= retry.max_wait_ms => {
+                    retries += 1;
+                    debug!(peer = %peer, wait_ms = wait, retries, "Retrying query");
+                    tokio::time::sleep(Duration::from_millis(wait)).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Pass on a successful query, or try the alternative providers named
+    /// in the error the provider answered with.
+    ///
+    /// Returns the first alternative's response, or the original error if
+    /// none serves the content.
+    pub(crate) async fn query_alternatives(
+        &self,
+        hash: &Hash,
+        payment_amount: Amount,
+        network: &Arc<dyn Network>,
+        result: OpsResult<QueryResponse>,
+    ) -> OpsResult<QueryResponse> {
+        let error = match result {
+            Ok(response) => return Ok(response),
+            Err(error) => error,
+        };
+        let OpsError::Network(NetworkError::QueryError {
+            alternative_providers,
+            ..
+        }) = &error
+        else {
+            return Err(error);
+        };
+        if !self.config.query_retry.try_alternatives {
+            return Err(error);
+        }
+
+        let local = network.local_peer_id();
+        let mut providers: Vec<_> = alternative_providers
+            .iter()
+            .take(MAX_ALTERNATIVE_PROVIDERS)
+            .filter_map(|provider| provider.parse::<nodalync_net::PeerId>().ok())
+            .filter(|provider| *provider != local)
+            .collect();
+        self.rank_providers(network, &mut providers);
+        for provider in providers {
+            if !network.connected_peers().contains(&provider)
+                && network.dial_peer(provider).await.is_err()
+            {
+                continue;
+            }
+            debug!(hash = %hash, provider = %provider, "Trying alternative provider");
+            if let Some(response) = self
+                .try_query_peer(hash, provider, payment_amount, network)
+                .await?
+            {
+                return Ok(response);
+            }
+        }
+        Err(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{OpsConfig, QueryLimitConfig, QueryRetryConfig};
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_test_utils::MockNetwork;
+    use nodalync_types::{ContentType, ErrorCode, L1Summary, Metadata, Visibility};
+    use nodalync_wire::AnnouncePayload;
+    use tempfile::TempDir;
+
+    fn create_test_ops(config: OpsConfig) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops =
+            DefaultNodeOperations::with_config(state, peer_id_from_public_key(&public_key), config);
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    fn query(hash: Hash) -> QueryRequestPayload {
+        QueryRequestPayload {
+            hash,
+            query: None,
+            payment: None,
+            version_spec: None,
+            payment_nonce: 0,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        }
+    }
+
+    /// Free content published by `provider`, and its answer to a query
+    /// from `requester`.
+    async fn publish(
+        provider: &DefaultNodeOperations,
+        requester: &DefaultNodeOperations,
+    ) -> (Hash, QueryResponsePayload) {
+        let content = b"Field notes from the survey";
+        let hash = provider
+            .create_content(content, Metadata::new("Notes", content.len() as u64))
+            .unwrap();
+        provider
+            .publish_content(&hash, Visibility::Shared, 0)
+            .await
+            .unwrap();
+        let response = provider
+            .handle_query_request(&requester.peer_id(), &query(hash))
+            .await
+            .unwrap();
+        (hash, response)
+    }
+
+    fn busy(hash: Hash, retry_after_ms: u64) -> QueryErrorPayload {
+        let mut error = QueryErrorPayload::new(hash, ErrorCode::RateLimited, "busy");
+        error.retry_after_ms = Some(retry_after_ms);
+        error
+    }
+
+    /// A client finding `provider`'s announcement of `hash` in the DHT of
+    /// `network`, naming the provider as `provider_peer`.
+    fn client_for(
+        config: OpsConfig,
+        provider_peer: nodalync_net::PeerId,
+        hash: Hash,
+        network: MockNetwork,
+    ) -> (DefaultNodeOperations, Arc<MockNetwork>, TempDir) {
+        let (mut client, temp) = create_test_ops(config);
+        let announcement = AnnouncePayload {
+            hash,
+            content_type: ContentType::L0,
+            title: "Notes".to_string(),
+            l1_summary: L1Summary::empty(hash),
+            price: 0,
+            addresses: vec![],
+            publisher_peer_id: Some(provider_peer.to_string()),
+            publisher_key: None,
+            signature: None,
+            delegation: None,
+        };
+        let network = Arc::new(network.with_dht_entry(hash, announcement));
+        client.set_network(Arc::clone(&network) as Arc<dyn Network>);
+        (client, network, temp)
+    }
+
+    #[tokio::test]
+    async fn test_query_error_payload_carries_retry_hints() {
+        let (owner, _owner_temp) = create_test_ops(OpsConfig::default());
+        let config = OpsConfig::default()
+            .with_query_limit(QueryLimitConfig::default().with_max_concurrent(1));
+        let (mut server, _temp) = create_test_ops(config);
+        let (requester, _requester_temp) = create_test_ops(OpsConfig::default());
+        let (hash, _) = publish(&owner, &requester).await;
+        let requester_peer = nodalync_net::PeerId::random();
+        let owner_peer = nodalync_net::PeerId::random();
+        server.set_network(Arc::new(
+            MockNetwork::new().with_peer_mapping(owner_peer, owner.peer_id()),
+        ));
+
+        // The server knows the manifest but doesn't hold the content, so it
+        // points to the owner
+        let manifest = owner.state.manifests.load(&hash).unwrap().unwrap();
+        server.state.manifests.store(&manifest).unwrap();
+        let error = server
+            .handle_query_request(&requester.peer_id(), &query(hash))
+            .await
+            .unwrap_err();
+        let payload = server.query_error_payload(hash, &requester_peer, &error);
+        assert_eq!(payload.error_code, ErrorCode::NotFound);
+        assert_eq!(payload.retry_after_ms, None);
+        assert_eq!(payload.alternative_providers, vec![owner_peer.to_string()]);
+
+        // Never back to the requester itself
+        let payload = server.query_error_payload(hash, &owner_peer, &error);
+        assert!(payload.alternative_providers.is_empty());
+
+        // Over the concurrency limit
+        let slot = server.acquire_query_slot().unwrap();
+        assert_eq!(server.queries_in_flight(), 1);
+        let error = server
+            .handle_query_request(&requester.peer_id(), &query(hash))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            OpsError::Busy {
+                retry_after_ms: 1_000
+            }
+        ));
+        let payload = server.query_error_payload(hash, &requester_peer, &error);
+        assert_eq!(payload.error_code, ErrorCode::RateLimited);
+        assert_eq!(payload.retry_after_ms, Some(1_000));
+        assert_eq!(payload.alternative_providers, vec![owner_peer.to_string()]);
+        drop(slot);
+        assert_eq!(server.queries_in_flight(), 0);
+
+        // Errors that another provider wouldn't fix carry no alternatives
+        let payload = server.query_error_payload(hash, &requester_peer, &OpsError::AccessDenied);
+        assert!(payload.alternative_providers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_query_waits_out_retry_delay() {
+        let (provider, _provider_temp) = create_test_ops(OpsConfig::default());
+        let (requester, _requester_temp) = create_test_ops(OpsConfig::default());
+        let (hash, response) = publish(&provider, &requester).await;
+        let provider_peer = nodalync_net::PeerId::random();
+        let network = MockNetwork::new()
+            .with_query_error(provider_peer, busy(hash, 10))
+            .with_query_response(hash, response);
+        let (client, network, _temp) =
+            client_for(OpsConfig::default(), provider_peer, hash, network);
+
+        let response = client.query_content(&hash, 0, None).await.unwrap();
+        assert_eq!(response.content, b"Field notes from the survey");
+        let requests = network.query_requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|(peer, _)| *peer == provider_peer));
+    }
+
+    #[tokio::test]
+    async fn test_query_gives_up_on_long_or_repeated_delays() {
+        let (provider, _provider_temp) = create_test_ops(OpsConfig::default());
+        let (requester, _requester_temp) = create_test_ops(OpsConfig::default());
+        let (hash, response) = publish(&provider, &requester).await;
+        let provider_peer = nodalync_net::PeerId::random();
+
+        // Longer than we are willing to wait
+        let network = MockNetwork::new()
+            .with_query_error(provider_peer, busy(hash, 60_000))
+            .with_query_response(hash, response.clone());
+        let (client, network, _temp) =
+            client_for(OpsConfig::default(), provider_peer, hash, network);
+        assert!(client.query_content(&hash, 0, None).await.is_err());
+        assert_eq!(network.query_requests().len(), 1);
+
+        // Still busy after every retry
+        let config =
+            OpsConfig::default().with_query_retry(QueryRetryConfig::default().with_max_retries(2));
+        let network = MockNetwork::new()
+            .with_query_error(provider_peer, busy(hash, 1))
+            .with_query_error(provider_peer, busy(hash, 1))
+            .with_query_error(provider_peer, busy(hash, 1))
+            .with_query_response(hash, response);
+        let (client, network, _temp) = client_for(config, provider_peer, hash, network);
+        assert!(client.query_content(&hash, 0, None).await.is_err());
+        assert_eq!(network.query_requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_query_tries_alternative_providers() {
+        let (provider, _provider_temp) = create_test_ops(OpsConfig::default());
+        let (requester, _requester_temp) = create_test_ops(OpsConfig::default());
+        let (hash, response) = publish(&provider, &requester).await;
+        let provider_peer = nodalync_net::PeerId::random();
+        let alternative = nodalync_net::PeerId::random();
+        let mut not_found = QueryErrorPayload::new(hash, ErrorCode::NotFound, "not here");
+        not_found.alternative_providers =
+            vec!["not a peer id".to_string(), alternative.to_string()];
+        let network = || {
+            MockNetwork::new()
+                .with_query_error(provider_peer, not_found.clone())
+                .with_query_response(hash, response.clone())
+        };
+
+        let (client, mock, _temp) =
+            client_for(OpsConfig::default(), provider_peer, hash, network());
+        let result = client.query_content(&hash, 0, None).await.unwrap();
+        assert_eq!(result.manifest.hash, hash);
+        let peers: Vec<_> = mock.query_requests().into_iter().map(|(p, _)| p).collect();
+        assert_eq!(peers, vec![provider_peer, alternative]);
+
+        // Not when alternatives are turned off
+        let config = OpsConfig::default()
+            .with_query_retry(QueryRetryConfig::default().with_try_alternatives(false));
+        let (client, mock, _temp) = client_for(config, provider_peer, hash, network());
+        assert!(client.query_content(&hash, 0, None).await.is_err());
+        assert_eq!(mock.query_requests().len(), 1);
+    }
+}
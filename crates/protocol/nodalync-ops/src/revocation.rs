@@ -0,0 +1,296 @@
+//! Key-compromise revocation.
+//!
+//! `nodalync init` writes a [`RevocationCertificate`] signed by the new
+//! identity key, for the owner to keep offline. If the key leaks, the
+//! certificate is broadcast (from any node) on the announcement topic.
+//! Peers that accept it record the key as revoked and from then on:
+//!
+//! - drop requests and broadcasts sent by it,
+//! - drop announcements it published, including ones already cached,
+//! - reject manifests it owns, before paying and before caching.
+//!
+//! A certificate only verifies against the key it revokes, so nobody else
+//! can revoke an identity, and a revocation is final.
+
+use nodalync_crypto::{peer_id_from_public_key, PeerId};
+use nodalync_store::{RevocationStore, SqliteRevocationStore};
+use nodalync_types::{Manifest, RevocationCertificate};
+use nodalync_valid::{
+    validate_manifest_not_revoked, validate_revocation, RevocationList, Validator,
+};
+use nodalync_wire::RevocationPayload;
+use tracing::{debug, info, warn};
+
+use crate::error::OpsResult;
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+/// Revocation lookups against the node's revocation store.
+///
+/// Lookup failures are logged and treated as not revoked.
+pub(crate) struct StoredRevocations<'a>(pub(crate) &'a SqliteRevocationStore);
+
+impl RevocationList for StoredRevocations<'_> {
+    fn is_revoked(&self, peer: &PeerId) -> bool {
+        self.0.is_revoked(peer).unwrap_or_else(|e| {
+            warn!(peer = %peer, error = %e, "Failed to look up key revocation");
+            false
+        })
+    }
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Get the revocation certificate for a peer, if its key was revoked.
+    pub fn get_revocation(&self, peer: &PeerId) -> OpsResult<Option<RevocationCertificate>> {
+        Ok(self.state.revocations.get(peer)?)
+    }
+
+    /// List all known key revocations, newest first.
+    pub fn list_revocations(&self) -> OpsResult<Vec<RevocationCertificate>> {
+        Ok(self.state.revocations.list()?)
+    }
+
+    /// Check whether a peer's key has been revoked.
+    pub fn is_peer_revoked(&self, peer: &PeerId) -> bool {
+        StoredRevocations(&self.state.revocations).is_revoked(peer)
+    }
+
+    /// Revoke a key with its revocation certificate and broadcast it.
+    ///
+    /// The certificate can be for any key, including our own; it only has
+    /// to verify. The broadcast is best-effort; the revocation applies
+    /// locally either way. Returns whether the key was newly revoked.
+    pub async fn revoke_key(&self, certificate: &RevocationCertificate) -> OpsResult<bool> {
+        validate_revocation(certificate)?;
+        let stored = self.apply_revocation(certificate)?;
+
+        if let Some(network) = self.network() {
+            let payload = RevocationPayload {
+                certificate: certificate.clone(),
+            };
+            if let Err(e) = network.broadcast_revocation(payload).await {
+                warn!(peer = %certificate.peer_id, "Revocation broadcast failed (applied locally): {}", e);
+            }
+        }
+
+        Ok(stored)
+    }
+
+    /// Handle a revocation certificate broadcast by a peer.
+    ///
+    /// Validates and applies it. Returns whether the key was newly revoked.
+    pub fn handle_revocation(&self, payload: &RevocationPayload) -> OpsResult<bool> {
+        validate_revocation(&payload.certificate)?;
+        self.apply_revocation(&payload.certificate)
+    }
+
+    /// Store a validated revocation and drop the key's announcements.
+    fn apply_revocation(&self, certificate: &RevocationCertificate) -> OpsResult<bool> {
+        let peer = certificate.peer_id;
+        let stored = self
+            .state
+            .revocations
+            .store(certificate, current_timestamp())?;
+        if !stored {
+            debug!(peer = %peer, "Key already revoked");
+            return Ok(false);
+        }
+
+        let mut removed = 0;
+        for announcement in self.state.list_announcements() {
+            let publisher = announcement
+                .publisher_key
+                .as_ref()
+                .map(peer_id_from_public_key);
+            if publisher == Some(peer) && self.state.remove_announcement(&announcement.hash) {
+                removed += 1;
+            }
+        }
+
+        if peer == self.peer_id() {
+            warn!(
+                "Our own identity key has been revoked; peers will reject our messages and content"
+            );
+        } else {
+            info!(peer = %peer, announcements = removed, "Identity key revoked");
+        }
+        Ok(true)
+    }
+
+    /// Refuse a manifest owned by a revoked key.
+    pub(crate) fn ensure_owner_not_revoked(&self, manifest: &Manifest) -> OpsResult<()> {
+        validate_manifest_not_revoked(manifest, &StoredRevocations(&self.state.revocations))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OpsConfig;
+    use crate::error::OpsError;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, PrivateKey};
+    use nodalync_net::NetworkEvent;
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_types::{ContentType, L1Summary, Metadata, Visibility};
+    use nodalync_valid::{sign_announcement, sign_revocation, ValidationError};
+    use nodalync_wire::{
+        create_message, encode_message, encode_payload, AnnouncePayload, MessageType,
+        PreviewRequestPayload,
+    };
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops = DefaultNodeOperations::with_config(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default(),
+        );
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    /// A leaked key and the revocation certificate made for it at init.
+    fn leaked_key() -> (PrivateKey, RevocationCertificate) {
+        let (private_key, public_key) = generate_identity();
+        let mut certificate =
+            RevocationCertificate::new(peer_id_from_public_key(&public_key), public_key, 1_000);
+        sign_revocation(&private_key, &mut certificate);
+        (private_key, certificate)
+    }
+
+    fn signed_announcement(private_key: &PrivateKey, content: &[u8]) -> AnnouncePayload {
+        let hash = content_hash(content);
+        let mut announcement = AnnouncePayload {
+            hash,
+            content_type: ContentType::L0,
+            title: "Notes".to_string(),
+            l1_summary: L1Summary::empty(hash),
+            price: 0,
+            addresses: vec![],
+            publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
+        };
+        sign_announcement(private_key, &mut announcement).unwrap();
+        announcement
+    }
+
+    fn is_key_revoked(result: OpsResult<()>) -> bool {
+        matches!(
+            result,
+            Err(OpsError::Validation(ValidationError::KeyRevoked { .. }))
+        )
+    }
+
+    #[tokio::test]
+    async fn test_revocation_broadcast_revokes_key() {
+        let (ops, _dir) = create_test_ops();
+        let (private_key, certificate) = leaked_key();
+        let peer = certificate.peer_id;
+        let announcement = signed_announcement(&private_key, b"published before the leak");
+        assert!(ops.store_verified_announcement(announcement.clone()));
+
+        // Relayed by the revoked key itself
+        let payload = RevocationPayload {
+            certificate: certificate.clone(),
+        };
+        let message = create_message(
+            MessageType::Revocation,
+            encode_payload(&payload).unwrap(),
+            peer,
+            current_timestamp(),
+            &private_key,
+        );
+        ops.handle_network_event(NetworkEvent::BroadcastReceived {
+            topic: "/nodalync/announce/1.0.0".to_string(),
+            data: encode_message(&message).unwrap(),
+        })
+        .await
+        .unwrap();
+        assert!(ops.is_peer_revoked(&peer));
+        assert_eq!(ops.get_revocation(&peer).unwrap(), Some(certificate));
+        assert!(!ops.handle_revocation(&payload).unwrap());
+
+        // Its announcements are dropped, cached or new
+        assert!(ops.state.get_announcement(&announcement.hash).is_none());
+        assert!(!ops.store_verified_announcement(announcement));
+        let later = signed_announcement(&private_key, b"published after the leak");
+        assert!(!ops.filter_and_store_announcement(&peer, later));
+        assert_eq!(ops.announcement_filter_stats().dropped_revoked, 1);
+
+        // And so are the manifests it owns
+        let content = b"signed with a leaked key";
+        let manifest = Manifest::new_l0(
+            content_hash(content),
+            peer,
+            Metadata::new("Leaked", content.len() as u64),
+            current_timestamp(),
+        );
+        assert!(is_key_revoked(ops.ensure_trusted(&manifest)));
+        assert!(is_key_revoked(ops.screen_remote_content(&manifest)));
+    }
+
+    #[test]
+    fn test_forged_revocation_rejected() {
+        let (ops, _dir) = create_test_ops();
+        let (_, certificate) = leaked_key();
+
+        // Signed by someone other than the key being revoked
+        let (other_key, _) = generate_identity();
+        let mut forged = certificate.clone();
+        sign_revocation(&other_key, &mut forged);
+        assert!(ops
+            .handle_revocation(&RevocationPayload {
+                certificate: forged
+            })
+            .is_err());
+        assert!(!ops.is_peer_revoked(&certificate.peer_id));
+        assert!(ops.list_revocations().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_requests_from_revoked_key_dropped() {
+        let (ops, _dir) = create_test_ops();
+        let content = b"Free notes";
+        let hash = ops
+            .create_content(content, Metadata::new("Notes", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 0)
+            .await
+            .unwrap();
+
+        let (private_key, certificate) = leaked_key();
+        let request = encode_message(&create_message(
+            MessageType::PreviewRequest,
+            encode_payload(&PreviewRequestPayload { hash }).unwrap(),
+            certificate.peer_id,
+            current_timestamp(),
+            &private_key,
+        ))
+        .unwrap();
+        let libp2p_peer = nodalync_net::PeerId::random();
+
+        let response = ops
+            .handle_inbound_request(&libp2p_peer, &request)
+            .await
+            .unwrap();
+        assert!(matches!(response, Some((MessageType::PreviewResponse, _))));
+
+        assert!(ops.revoke_key(&certificate).await.unwrap());
+        let response = ops
+            .handle_inbound_request(&libp2p_peer, &request)
+            .await
+            .unwrap();
+        assert!(response.is_none());
+    }
+}
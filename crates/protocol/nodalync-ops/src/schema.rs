@@ -0,0 +1,188 @@
+//! Metadata schema operations.
+//!
+//! Structured metadata fields are validated against the schema their URI
+//! resolves to. Schemas resolve from the store's schema cache, falling back
+//! to the built-in schemas in `nodalync-valid` (which are cached on first
+//! use). Other schemas must be registered before content can use them.
+//!
+//! Local content is rejected if its fields don't validate. Manifests
+//! received from peers keep their fields only if they validate against a
+//! known schema, so every manifest in the store carries well-formed fields.
+
+use nodalync_store::MetadataSchemaStore;
+use nodalync_types::{Manifest, Metadata};
+use nodalync_valid::{builtin_schema, validate_schema, Validator};
+use tracing::warn;
+
+use crate::error::OpsResult;
+use crate::extraction::L1Extractor;
+use crate::node_ops::NodeOperations;
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Register a metadata schema under a URI.
+    ///
+    /// The schema is checked with `validate_schema` and replaces any schema
+    /// cached under the same URI.
+    pub fn register_schema(&self, uri: &str, schema: &str) -> OpsResult<()> {
+        validate_schema(schema)?;
+        self.state.schemas.put(uri, schema)?;
+        Ok(())
+    }
+
+    /// Resolve a schema URI to its schema.
+    ///
+    /// Returns `None` if the URI is neither cached nor built in.
+    pub fn resolve_schema(&self, uri: &str) -> OpsResult<Option<String>> {
+        if let Some(schema) = self.state.schemas.get(uri)? {
+            return Ok(Some(schema));
+        }
+        let Some(schema) = builtin_schema(uri) else {
+            return Ok(None);
+        };
+        self.state.schemas.put(uri, schema)?;
+        Ok(Some(schema.to_string()))
+    }
+
+    /// Validate structured metadata fields against their schema.
+    pub(crate) fn validate_metadata_fields(&self, metadata: &Metadata) -> OpsResult<()> {
+        let schema = match metadata.schema {
+            Some(ref uri) => self.resolve_schema(uri)?,
+            None => None,
+        };
+        self.validator
+            .validate_structured_metadata(metadata, schema.as_deref())?;
+        Ok(())
+    }
+
+    /// Drop structured fields from a peer's manifest unless they validate.
+    pub(crate) fn check_remote_metadata(&self, manifest: &mut Manifest) {
+        if manifest.metadata.schema.is_none() && manifest.metadata.fields.is_none() {
+            return;
+        }
+        if let Err(e) = self.validate_metadata_fields(&manifest.metadata) {
+            warn!(hash = %manifest.hash, error = %e, "Dropping invalid metadata fields from peer");
+            manifest.metadata.schema = None;
+            manifest.metadata.fields = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultNodeOperations, OpsError};
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key, PeerId};
+    use nodalync_store::{ManifestStore, NodeState, NodeStateConfig};
+    use nodalync_valid::{ValidationError, CITATION_SCHEMA_URI};
+    use tempfile::TempDir;
+
+    const PAPER_SCHEMA: &str = r#"{"type":"object","required":["doi"],
+        "properties":{"doi":{"type":"string"}}}"#;
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let config = NodeStateConfig::new(temp_dir.path());
+        let state = NodeState::open(config).unwrap();
+        let ops = DefaultNodeOperations::with_defaults(state, test_peer_id());
+        (ops, temp_dir)
+    }
+
+    #[test]
+    fn test_create_with_builtin_schema() {
+        let (ops, _temp) = create_test_ops();
+        let content = b"A paper";
+        let metadata = Metadata::new("Paper", content.len() as u64)
+            .with_fields(CITATION_SCHEMA_URI, r#"{"authors":["Ada"],"year":1843}"#);
+
+        let hash = ops.create_content(content, metadata).unwrap();
+        let manifest = ops.state.manifests.load(&hash).unwrap().unwrap();
+        assert_eq!(
+            manifest.metadata.schema.as_deref(),
+            Some(CITATION_SCHEMA_URI)
+        );
+        assert!(manifest.metadata.fields.unwrap().contains("Ada"));
+
+        // The built-in schema is now cached
+        assert_eq!(
+            ops.state.schemas.list().unwrap(),
+            vec![CITATION_SCHEMA_URI.to_string()]
+        );
+    }
+
+    #[test]
+    fn test_create_rejects_invalid_fields() {
+        let (ops, _temp) = create_test_ops();
+        let content = b"A paper";
+        let metadata = Metadata::new("Paper", content.len() as u64)
+            .with_fields(CITATION_SCHEMA_URI, r#"{"authors":[]}"#);
+
+        let result = ops.create_content(content, metadata);
+        assert!(matches!(
+            result,
+            Err(OpsError::Validation(ValidationError::InvalidFields { .. }))
+        ));
+        assert!(ops
+            .state
+            .manifests
+            .load(&content_hash(content))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_register_schema() {
+        let (ops, _temp) = create_test_ops();
+        let content = b"A paper";
+        let metadata = Metadata::new("Paper", content.len() as u64)
+            .with_fields("example:paper", r#"{"doi":"10.1000/1"}"#);
+
+        // Unknown until registered
+        assert!(matches!(
+            ops.create_content(content, metadata.clone()),
+            Err(OpsError::Validation(ValidationError::UnknownSchema { .. }))
+        ));
+
+        assert!(matches!(
+            ops.register_schema("example:paper", r#"{"type":"object","oneOf":[]}"#),
+            Err(OpsError::Validation(ValidationError::InvalidSchema { .. }))
+        ));
+        ops.register_schema("example:paper", PAPER_SCHEMA).unwrap();
+        ops.create_content(content, metadata).unwrap();
+    }
+
+    #[test]
+    fn test_remote_metadata_checked() {
+        let (ops, _temp) = create_test_ops();
+        ops.register_schema("example:paper", PAPER_SCHEMA).unwrap();
+
+        let content = b"Remote paper";
+        let hash = content_hash(content);
+        let remote = |fields: &str, schema: &str| {
+            let metadata =
+                Metadata::new("Remote", content.len() as u64).with_fields(schema, fields);
+            Manifest::new_l0(hash, test_peer_id(), metadata, 1000)
+        };
+
+        let mut valid = remote(r#"{"doi":"10.1000/1"}"#, "example:paper");
+        ops.check_remote_metadata(&mut valid);
+        assert!(valid.metadata.fields.is_some());
+
+        let mut invalid = remote(r#"{"doi":1}"#, "example:paper");
+        ops.check_remote_metadata(&mut invalid);
+        assert!(invalid.metadata.schema.is_none());
+        assert!(invalid.metadata.fields.is_none());
+
+        let mut unknown = remote(r#"{"doi":"10.1000/1"}"#, "example:unknown");
+        ops.check_remote_metadata(&mut unknown);
+        assert!(unknown.metadata.fields.is_none());
+    }
+}
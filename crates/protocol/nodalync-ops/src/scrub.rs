@@ -0,0 +1,278 @@
+//! Content integrity scrubbing.
+//!
+//! Bit rot or manual edits in the content directory would otherwise go
+//! unnoticed until a query fails. A scrub re-hashes every stored blob
+//! against its manifest. A blob that no longer matches is moved to
+//! quarantine so it is never served, then repaired from a verified copy in
+//! the local cache or, failing that, from a known provider on the network.
+//!
+//! Network repair only uses free queries: content that has to be paid for
+//! again stays quarantined for the operator to deal with.
+
+use nodalync_crypto::{Hash, PeerId, Timestamp};
+use nodalync_store::{CacheStore, ContentStore, ManifestFilter, ManifestStore};
+use nodalync_valid::Validator;
+use tracing::{info, warn};
+
+use crate::error::OpsResult;
+use crate::extraction::L1Extractor;
+use crate::helpers::verify_content_hash;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+/// What happened to a blob that failed its integrity check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrubOutcome {
+    /// Restored from a verified copy in the local cache.
+    RestoredFromCache,
+    /// Re-fetched from a provider on the network.
+    Refetched,
+    /// No good copy was available; the blob stays in quarantine.
+    Quarantined,
+}
+
+/// A blob that failed its integrity check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScrubIssue {
+    /// Content hash from the manifest.
+    pub hash: Hash,
+    /// Content title, for reporting.
+    pub title: String,
+    /// How the mismatch was handled.
+    pub outcome: ScrubOutcome,
+}
+
+/// Result of a scrub run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScrubReport {
+    /// When the scrub started.
+    pub started_at: Timestamp,
+    /// When the scrub finished.
+    pub finished_at: Timestamp,
+    /// Number of blobs re-hashed.
+    pub checked: u32,
+    /// Blobs that failed their check, in manifest order.
+    pub issues: Vec<ScrubIssue>,
+}
+
+impl ScrubReport {
+    /// Whether every checked blob matched its manifest.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Number of blobs left in quarantine by this run.
+    pub fn quarantined(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|i| i.outcome == ScrubOutcome::Quarantined)
+            .count()
+    }
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Re-hash every stored blob against its manifest.
+    ///
+    /// 1. Loads each manifest whose content is stored locally
+    /// 2. Compares the blob's hash with the manifest hash
+    /// 3. Quarantines mismatches
+    /// 4. Restores them from the cache or a known provider when possible
+    pub async fn scrub_content(&self) -> OpsResult<ScrubReport> {
+        let started_at = current_timestamp();
+        let manifests = self.state.manifests.list(ManifestFilter::default())?;
+
+        let mut checked = 0;
+        let mut issues = Vec::new();
+        for manifest in manifests {
+            let Some(content) = self.state.content.load(&manifest.hash)? else {
+                continue;
+            };
+            checked += 1;
+            if verify_content_hash(&content, &manifest.hash) {
+                continue;
+            }
+            drop(content);
+
+            warn!(hash = %manifest.hash, "Stored content does not match its hash, quarantining");
+            self.state.content.quarantine(&manifest.hash)?;
+
+            let outcome = if self.restore_from_cache(&manifest.hash)? {
+                ScrubOutcome::RestoredFromCache
+            } else if self
+                .refetch_content(&manifest.hash, &manifest.owner)
+                .await?
+            {
+                ScrubOutcome::Refetched
+            } else {
+                ScrubOutcome::Quarantined
+            };
+            if outcome != ScrubOutcome::Quarantined {
+                self.state.content.release_quarantined(&manifest.hash)?;
+            }
+
+            issues.push(ScrubIssue {
+                hash: manifest.hash,
+                title: manifest.metadata.title,
+                outcome,
+            });
+        }
+
+        let report = ScrubReport {
+            started_at,
+            finished_at: current_timestamp(),
+            checked,
+            issues,
+        };
+        info!(
+            checked = report.checked,
+            issues = report.issues.len(),
+            quarantined = report.quarantined(),
+            "Content scrub complete"
+        );
+        Ok(report)
+    }
+
+    /// Restore content from a verified copy in the local cache.
+    fn restore_from_cache(&self, hash: &Hash) -> OpsResult<bool> {
+        let Some(cached) = self.state.cache.get(hash)? else {
+            return Ok(false);
+        };
+        if !verify_content_hash(&cached.content, hash) {
+            return Ok(false);
+        }
+        self.state.content.store_verified(hash, &cached.content)?;
+        Ok(true)
+    }
+
+    /// Re-fetch content with a free query to a known provider.
+    ///
+    /// Providers are the owner and the publisher from the content's
+    /// announcement, if either is a peer other than us.
+    async fn refetch_content(&self, hash: &Hash, owner: &PeerId) -> OpsResult<bool> {
+        let Some(network) = self.network().cloned() else {
+            return Ok(false);
+        };
+
+        let providers = self.content_providers(&network, hash, owner);
+        for provider in providers {
+            match self.try_query_peer(hash, provider, 0, &network).await {
+                Ok(Some(response)) => {
+                    self.state.content.store_verified(hash, &response.content)?;
+                    info!(hash = %hash, provider = %provider, "Re-fetched quarantined content");
+                    return Ok(true);
+                }
+                Ok(None) => {}
+                Err(e) => warn!(hash = %hash, provider = %provider, error = %e, "Re-fetch failed"),
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key, Signature};
+    use nodalync_store::{CachedContent, NodeState, NodeStateConfig};
+    use nodalync_types::Metadata;
+    use nodalync_wire::PaymentReceipt;
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let config = NodeStateConfig::new(temp_dir.path());
+        let state = NodeState::open(config).unwrap();
+        let (_, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+        let ops = DefaultNodeOperations::with_defaults(state, peer_id);
+        (ops, temp_dir)
+    }
+
+    /// Overwrite a stored blob in place, as bit rot or a manual edit would.
+    fn corrupt(temp: &TempDir, hash: &Hash) {
+        let hex = format!("{}", hash);
+        let path = temp.path().join("content").join(&hex[..4]).join(&hex);
+        std::fs::write(path, b"corrupted bytes").unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scrub_clean_store() {
+        let (ops, _temp) = create_test_ops();
+        ops.create_content(b"healthy", Metadata::new("Healthy", 7))
+            .unwrap();
+
+        let report = ops.scrub_content().await.unwrap();
+        assert_eq!(report.checked, 1);
+        assert!(report.is_clean());
+    }
+
+    #[tokio::test]
+    async fn test_scrub_quarantines_mismatch() {
+        let (ops, temp) = create_test_ops();
+        let good = ops
+            .create_content(b"healthy", Metadata::new("Healthy", 7))
+            .unwrap();
+        let bad = ops
+            .create_content(b"will rot", Metadata::new("Rotten", 8))
+            .unwrap();
+        corrupt(&temp, &bad);
+
+        let report = ops.scrub_content().await.unwrap();
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.quarantined(), 1);
+        assert_eq!(report.issues[0].hash, bad);
+        assert_eq!(report.issues[0].title, "Rotten");
+        assert_eq!(report.issues[0].outcome, ScrubOutcome::Quarantined);
+
+        // The bad blob is never served; the good one is untouched
+        assert!(ops.state.content.load(&bad).unwrap().is_none());
+        assert_eq!(ops.state.content.quarantined().unwrap(), vec![bad]);
+        assert!(ops.state.content.exists(&good));
+
+        // Nothing left to check on the next run
+        let report = ops.scrub_content().await.unwrap();
+        assert_eq!(report.checked, 1);
+        assert!(report.is_clean());
+    }
+
+    #[tokio::test]
+    async fn test_scrub_restores_from_cache() {
+        let (ops, temp) = create_test_ops();
+        let content = b"cached elsewhere";
+        let hash = ops
+            .create_content(content, Metadata::new("Cached", content.len() as u64))
+            .unwrap();
+        let receipt = PaymentReceipt {
+            payment_id: hash,
+            amount: 0,
+            timestamp: 0,
+            channel_nonce: 0,
+            distributor_signature: Signature::from_bytes([0u8; 64]),
+            app_fee: 0,
+            app_fee_recipient: None,
+            referral_fee: 0,
+            referrer: None,
+        };
+        ops.state
+            .cache
+            .cache(CachedContent::new(
+                hash,
+                content.to_vec(),
+                ops.peer_id(),
+                0,
+                receipt,
+            ))
+            .unwrap();
+        corrupt(&temp, &hash);
+
+        let report = ops.scrub_content().await.unwrap();
+        assert_eq!(report.issues[0].outcome, ScrubOutcome::RestoredFromCache);
+        assert_eq!(report.quarantined(), 0);
+        assert_eq!(ops.state.content.load(&hash).unwrap().unwrap(), content);
+        assert!(ops.state.content.quarantined().unwrap().is_empty());
+    }
+}
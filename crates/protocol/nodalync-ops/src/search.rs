@@ -0,0 +1,219 @@
+//! Federated search fan-out.
+//!
+//! A network search sends SEARCH to the most reputable connected peers at
+//! once, each bounded by `OpsConfig::search.peer_timeout_ms`, so one slow
+//! peer no longer holds up the others. Results are merged by content hash.
+//! When several peers offer the same content, the cheapest offer wins, then
+//! the most reputable peer, then the fastest response.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use futures::stream::{self, StreamExt};
+use nodalync_crypto::Hash;
+use nodalync_store::PeerStore;
+use nodalync_valid::Validator;
+use nodalync_wire::{SearchFilters, SearchPayload, SearchResult};
+use tracing::debug;
+
+use crate::extraction::L1Extractor;
+use crate::node_ops::NodeOperations;
+
+/// A search result from a peer, with who sent it and how fast.
+#[derive(Debug, Clone)]
+pub(crate) struct PeerSearchHit {
+    /// The peer's result.
+    pub result: SearchResult,
+    /// Peer that returned the result.
+    pub peer: nodalync_net::PeerId,
+    /// The peer's reputation (0 if unknown).
+    pub reputation: i64,
+    /// Round-trip time of the peer's response.
+    pub latency_ms: u64,
+}
+
+impl PeerSearchHit {
+    /// Ranking key: lower is a better offer.
+    fn rank(&self) -> (u64, Reverse<i64>, u64) {
+        (self.result.price, Reverse(self.reputation), self.latency_ms)
+    }
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Send a search to the top connected peers concurrently.
+    ///
+    /// Peers that fail or miss the deadline are skipped. Results that don't
+    /// match `filters` are dropped before merging, in case a peer ignores
+    /// some of them. Returns one hit per content hash, in the order the
+    /// content was first seen from peers in rank order.
+    pub(crate) async fn fan_out_search(
+        &self,
+        payload: &SearchPayload,
+        filters: &SearchFilters,
+    ) -> Vec<PeerSearchHit> {
+        let Some(network) = self.network().cloned() else {
+            return Vec::new();
+        };
+        let peers = self.rank_search_peers(network.connected_peers());
+        if peers.is_empty() {
+            return Vec::new();
+        }
+
+        let timeout = Duration::from_millis(self.config.search.peer_timeout_ms);
+        let concurrency = peers.len();
+        let responses: Vec<_> = stream::iter(peers)
+            .map(|(peer, reputation)| {
+                let network = network.clone();
+                let payload = payload.clone();
+                async move {
+                    let started = Instant::now();
+                    let response =
+                        tokio::time::timeout(timeout, network.send_search(peer, payload)).await;
+                    (peer, reputation, started.elapsed(), response)
+                }
+            })
+            // All at once, but results stay in peer rank order
+            .buffered(concurrency)
+            .collect()
+            .await;
+
+        let mut hits = Vec::new();
+        for (peer, reputation, elapsed, response) in responses {
+            let response = match response {
+                Ok(Ok(response)) => response,
+                Ok(Err(e)) => {
+                    debug!(peer = %peer, error = %e, "Peer search failed");
+                    continue;
+                }
+                Err(_) => {
+                    debug!(peer = %peer, "Peer search timed out");
+                    continue;
+                }
+            };
+            debug!(
+                peer = %peer,
+                results_count = response.results.len(),
+                latency_ms = elapsed.as_millis() as u64,
+                "Received search response from peer"
+            );
+            hits.extend(
+                response
+                    .results
+                    .into_iter()
+                    .filter(|result| filters.matches_result(result))
+                    .map(|result| PeerSearchHit {
+                        result,
+                        peer,
+                        reputation,
+                        latency_ms: elapsed.as_millis() as u64,
+                    }),
+            );
+        }
+
+        merge_search_hits(hits)
+    }
+
+    /// Pick the peers a search fans out to, most reputable first.
+    fn rank_search_peers(
+        &self,
+        peers: Vec<nodalync_net::PeerId>,
+    ) -> Vec<(nodalync_net::PeerId, i64)> {
+        let mut ranked: Vec<_> = peers
+            .into_iter()
+            .map(|peer| {
+                let reputation = self
+                    .network()
+                    .and_then(|network| network.nodalync_peer_id(&peer))
+                    .and_then(|id| self.state.peers.get(&id).ok().flatten())
+                    .map(|info| info.reputation)
+                    .unwrap_or(0);
+                (peer, reputation)
+            })
+            .collect();
+        // Stable, so equally reputable peers keep connection order
+        ranked.sort_by_key(|(_, reputation)| Reverse(*reputation));
+        ranked.truncate(self.config.search.max_peers);
+        ranked
+    }
+}
+
+/// Merge peer hits by content hash, keeping the best offer for each.
+fn merge_search_hits(hits: Vec<PeerSearchHit>) -> Vec<PeerSearchHit> {
+    let mut merged: Vec<PeerSearchHit> = Vec::new();
+    let mut index: HashMap<Hash, usize> = HashMap::new();
+    for hit in hits {
+        match index.get(&hit.result.hash) {
+            Some(&i) => {
+                if hit.rank() < merged[i].rank() {
+                    merged[i] = hit;
+                }
+            }
+            None => {
+                index.insert(hit.result.hash, merged.len());
+                merged.push(hit);
+            }
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, PeerId};
+    use nodalync_types::{ContentType, L1Summary};
+
+    fn hit(content: &[u8], price: u64, reputation: i64, latency_ms: u64) -> PeerSearchHit {
+        let hash = content_hash(content);
+        PeerSearchHit {
+            result: SearchResult {
+                hash,
+                content_type: ContentType::L0,
+                title: "Result".to_string(),
+                owner: PeerId([1u8; 20]),
+                l1_summary: L1Summary::empty(hash),
+                price,
+                total_queries: 0,
+                relevance_score: 1.0,
+                publisher_addresses: vec![],
+            },
+            peer: nodalync_net::PeerId::random(),
+            reputation,
+            latency_ms,
+        }
+    }
+
+    #[test]
+    fn test_merge_prefers_cheapest_then_reputable_then_fastest() {
+        let a_pricey = hit(b"a", 100, 50, 10);
+        let a_cheap = hit(b"a", 50, 0, 900);
+        let b_slow = hit(b"b", 10, 5, 800);
+        let b_fast = hit(b"b", 10, 5, 20);
+        let b_trusted = hit(b"b", 10, 9, 500);
+        let c = hit(b"c", 0, 0, 0);
+
+        let merged = merge_search_hits(vec![
+            a_pricey,
+            b_slow,
+            a_cheap.clone(),
+            b_fast,
+            c.clone(),
+            b_trusted.clone(),
+        ]);
+
+        // First-seen order, one hit per hash
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].peer, a_cheap.peer);
+        assert_eq!(merged[1].peer, b_trusted.peer);
+        assert_eq!(merged[2].peer, c.peer);
+
+        // Same price and reputation: the fastest wins
+        let merged = merge_search_hits(vec![hit(b"d", 10, 5, 800), hit(b"d", 10, 5, 20)]);
+        assert_eq!(merged[0].latency_ms, 20);
+    }
+}
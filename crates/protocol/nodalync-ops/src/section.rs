@@ -0,0 +1,522 @@
+//! Sections of content sold on their own.
+//!
+//! A manifest can list [`ContentSection`]s, each a byte range of the content
+//! with its own price and a hash bound to its offset. A query naming a
+//! section pays that section's price rather than the whole document's, and
+//! is served just the slice:
+//!
+//! - The provider checks the payment against the section price, settles and
+//!   distributes it like any other payment, and serves the slice without a
+//!   delivery commitment, which only covers whole content
+//! - The consumer checks the slice against the section hash in the
+//!   provider's manifest before crediting the payment to its channel
+//!
+//! Sliced content is not cached: the cache holds whole content only.
+
+use std::borrow::Cow;
+use std::ops::Range;
+use std::sync::Arc;
+
+use nodalync_crypto::{Hash, Signature};
+use nodalync_net::Network;
+use nodalync_store::{ChannelStore, ContentStore, ManifestStore};
+use nodalync_types::{Amount, ContentSection, ContentType, Manifest};
+use nodalync_valid::{validate_content, validate_metadata, Validator};
+use nodalync_wire::{ContentBytes, PaymentReceipt, PreviewRequestPayload, QueryRequestPayload};
+use tracing::warn;
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+use crate::redaction::markdown_heading;
+use crate::referral::current_referrer;
+use crate::trace::request_id_or_new;
+
+/// Response from a section query.
+#[derive(Debug, Clone)]
+pub struct SectionResponse {
+    /// The section bought.
+    pub section: ContentSection,
+    /// The section's bytes.
+    pub content: Vec<u8>,
+    /// The content manifest.
+    pub manifest: Manifest,
+    /// Payment receipt.
+    pub receipt: PaymentReceipt,
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Set the priced sections of owned content; an empty list removes them.
+    ///
+    /// The sections are checked against the stored content, so each must
+    /// have been built from it.
+    pub fn set_content_sections(
+        &self,
+        hash: &Hash,
+        sections: Vec<ContentSection>,
+    ) -> OpsResult<()> {
+        // Load manifest
+        let mut manifest = self
+            .state
+            .manifests
+            .load(hash)?
+            .ok_or(OpsError::ManifestNotFound(*hash))?;
+
+        // Verify ownership
+        if manifest.owner != self.peer_id() {
+            return Err(OpsError::AccessDenied);
+        }
+
+        // A collection is sold as a bundle of its items
+        if !sections.is_empty() && manifest.content_type == ContentType::Collection {
+            return Err(OpsError::invalid_operation(
+                "collections can't be sold by section",
+            ));
+        }
+
+        manifest.metadata.sections = sections;
+        if manifest.metadata.sections.is_empty() {
+            validate_metadata(&manifest)?;
+        } else {
+            let content = self
+                .state
+                .content
+                .load(hash)?
+                .ok_or(OpsError::NotFound(*hash))?;
+            validate_content(&content, &manifest)?;
+        }
+
+        manifest.updated_at = current_timestamp();
+        self.state.manifests.update(&manifest)?;
+
+        Ok(())
+    }
+
+    /// Price sections of owned Markdown content by heading.
+    ///
+    /// Each `(heading, price)` pair becomes a section; see
+    /// [`heading_sections`].
+    pub fn set_section_prices(&self, hash: &Hash, prices: &[(String, Amount)]) -> OpsResult<()> {
+        let content = self
+            .state
+            .content
+            .load(hash)?
+            .ok_or(OpsError::NotFound(*hash))?;
+        let sections = heading_sections(&content, prices)?;
+        self.set_content_sections(hash, sections)
+    }
+
+    /// Query one section of content, paying only its price.
+    ///
+    /// Own content is read locally. Otherwise the provider is asked for a
+    /// fresh preview, whose manifest lists the sections and their prices,
+    /// and then for the section itself; the slice must match the section
+    /// hash. Fails with `PaymentInsufficient` if the section costs more than
+    /// `max_payment`.
+    pub async fn query_section(
+        &self,
+        hash: &Hash,
+        index: u32,
+        max_payment: Amount,
+    ) -> OpsResult<SectionResponse> {
+        let timestamp = current_timestamp();
+
+        // 1. Own content needs no payment
+        if let Some(manifest) = self.state.manifests.load(hash)? {
+            if manifest.owner == self.peer_id() {
+                let section = manifest
+                    .metadata
+                    .section(index)
+                    .cloned()
+                    .ok_or(OpsError::SectionNotFound { hash: *hash, index })?;
+                let content = self
+                    .state
+                    .content
+                    .load(hash)?
+                    .ok_or(OpsError::NotFound(*hash))?;
+                let slice = content
+                    .get(section.range())
+                    .ok_or(OpsError::ContentHashMismatch)?
+                    .to_vec();
+
+                return Ok(SectionResponse {
+                    section,
+                    content: slice,
+                    manifest,
+                    receipt: PaymentReceipt {
+                        payment_id: *hash,
+                        amount: 0, // No payment for own content
+                        timestamp,
+                        channel_nonce: 0,
+                        distributor_signature: Signature::from_bytes([0u8; 64]),
+                        app_fee: 0,
+                        app_fee_recipient: None,
+                        referral_fee: 0,
+                        referrer: None,
+                    },
+                });
+            }
+        }
+
+        // 2-3. Announcements don't list sections, so ask the provider for
+        // its manifest
+        let (network, provider, manifest) = self.provider_manifest(hash).await?;
+        let section = manifest
+            .metadata
+            .section(index)
+            .cloned()
+            .ok_or(OpsError::SectionNotFound { hash: *hash, index })?;
+        if section.price > max_payment {
+            return Err(OpsError::PaymentInsufficient);
+        }
+
+        // 4. Sign the payment for paid sections
+        let owner = manifest.owner;
+        let (payment, payment_nonce) = if section.price > 0 {
+            let channel = self
+                .state
+                .channels
+                .get(&owner)?
+                .ok_or(OpsError::ChannelRequired)?;
+            self.ensure_trusted(&manifest)?;
+            self.check_revenue_claim(&manifest).await?;
+            self.check_publisher_bond(&manifest).await?;
+
+            let (payment, nonce) = self.sign_tracked_payment(
+                &owner,
+                &channel,
+                section.price,
+                *hash,
+                manifest.provenance.root_l0l1.clone(),
+            )?;
+            (Some(payment), nonce)
+        } else {
+            (None, 0)
+        };
+
+        let request = QueryRequestPayload {
+            hash: *hash,
+            query: None,
+            payment: payment.clone(),
+            version_spec: None,
+            payment_nonce,
+            capability: None,
+            recipient_key: self.recipient_key(),
+            challenge_response: None,
+            request_id: Some(request_id_or_new()),
+            section: Some(index),
+            unit: None,
+            referrer: current_referrer(),
+            entitlement: None,
+        };
+
+        // 5. Fetch the slice and check it against the section we paid for
+        let mut response = self
+            .send_query_answering_challenge(&network, provider, request)
+            .await?;
+        self.open_response(&mut response)?;
+        if response.section != Some(index) || !section.verify(&response.content) {
+            warn!(hash = %hash, index, provider = %provider, "Section does not match its hash");
+            return Err(OpsError::ContentHashMismatch);
+        }
+
+        // 6. Update channel balance after successful payment
+        if let Some(payment) = payment {
+            self.update_payment_channel(&owner, payment)?;
+        }
+        self.screen_remote_content(&manifest)?;
+
+        Ok(SectionResponse {
+            section,
+            content: response.content.into_vec(),
+            manifest,
+            receipt: response.payment_receipt,
+        })
+    }
+
+    /// Find the provider of remote content and fetch its manifest.
+    ///
+    /// Announcements carry neither sections nor metered units, so queries
+    /// for a part of the content need the provider's own manifest.
+    pub(crate) async fn provider_manifest(
+        &self,
+        hash: &Hash,
+    ) -> OpsResult<(Arc<dyn Network>, nodalync_net::PeerId, Manifest)> {
+        let network = self.network().cloned().ok_or(OpsError::NotFound(*hash))?;
+        let preview = self.preview_content(hash).await?;
+        let provider = preview
+            .provider_peer_id
+            .as_deref()
+            .and_then(|p| p.parse::<nodalync_net::PeerId>().ok())
+            .or_else(|| network.libp2p_peer_id(&preview.manifest.owner))
+            .ok_or(OpsError::PeerIdNotFound)?;
+
+        let remote = network
+            .send_preview_request(provider, PreviewRequestPayload { hash: *hash })
+            .await?;
+        let manifest = remote.manifest;
+        if manifest.hash != *hash {
+            return Err(OpsError::ContentHashMismatch);
+        }
+        validate_metadata(&manifest)?;
+        Ok((network, provider, manifest))
+    }
+
+    /// Load the bytes a query is served: the whole content, or the section
+    /// or metered unit it asked for.
+    pub(crate) fn load_served_content(
+        &self,
+        request: &QueryRequestPayload,
+        manifest: &Manifest,
+    ) -> OpsResult<ContentBytes> {
+        let content: ContentBytes = self
+            .state
+            .content
+            .load_mapped(&request.hash)?
+            .ok_or(OpsError::NotFound(request.hash))?
+            .into();
+
+        let Some(range) = served_range(request, manifest)? else {
+            return Ok(content);
+        };
+        let slice = content.get(range).ok_or(OpsError::ContentHashMismatch)?;
+        Ok(slice.into())
+    }
+}
+
+/// The byte range of the section or metered unit a query names, or `None`
+/// for the whole content.
+fn served_range(
+    request: &QueryRequestPayload,
+    manifest: &Manifest,
+) -> OpsResult<Option<Range<usize>>> {
+    let range = match (request.section, request.unit) {
+        (None, None) => return Ok(None),
+        (Some(index), _) => manifest
+            .metadata
+            .section(index)
+            .ok_or(OpsError::SectionNotFound {
+                hash: request.hash,
+                index,
+            })?
+            .range(),
+        (None, Some(index)) => manifest
+            .metadata
+            .metering
+            .as_ref()
+            .and_then(|units| units.chunk_range(index, manifest.metadata.content_size))
+            .ok_or(OpsError::UnitNotFound {
+                hash: request.hash,
+                index,
+            })?,
+    };
+    Ok(Some(range))
+}
+
+/// The bytes a query will be served, from the manifest: the whole content
+/// or the section or metered unit it names, cut to a trial's free bytes
+/// when it carries neither a payment nor an entitlement for paid content.
+pub(crate) fn served_size(request: &QueryRequestPayload, manifest: &Manifest) -> OpsResult<u64> {
+    let size = match served_range(request, manifest)? {
+        Some(range) => range.len() as u64,
+        None => manifest.metadata.content_size,
+    };
+    let trial = request.payment.is_none()
+        && request.entitlement.is_none()
+        && priced_manifest(manifest, request)?.economics.price > 0;
+    Ok(match manifest.access.trial.and_then(|trial| trial.bytes) {
+        Some(bytes) if trial => size.min(bytes),
+        _ => size,
+    })
+}
+
+/// The manifest a query is priced against.
+///
+/// For a section or metered unit, a copy carrying its price in place of the
+/// document's, without any dynamic pricing rule; otherwise the manifest
+/// itself. A query can't name both.
+pub(crate) fn priced_manifest<'a>(
+    manifest: &'a Manifest,
+    request: &QueryRequestPayload,
+) -> OpsResult<Cow<'a, Manifest>> {
+    let price = match (request.section, request.unit) {
+        (None, None) => return Ok(Cow::Borrowed(manifest)),
+        (Some(_), Some(_)) => {
+            return Err(OpsError::invalid_operation(
+                "a query can't name both a section and a metered unit",
+            ))
+        }
+        (Some(index), None) => {
+            manifest
+                .metadata
+                .section(index)
+                .ok_or(OpsError::SectionNotFound {
+                    hash: manifest.hash,
+                    index,
+                })?
+                .price
+        }
+        (None, Some(index)) => manifest.unit_price(index).ok_or(OpsError::UnitNotFound {
+            hash: manifest.hash,
+            index,
+        })?,
+    };
+
+    let mut priced = manifest.clone();
+    priced.economics.price = price;
+    priced.economics.pricing = None;
+    Ok(Cow::Owned(priced))
+}
+
+/// Priced sections of Markdown content, one per named heading.
+///
+/// A section runs from its heading to the next heading of the same or a
+/// higher level, so a priced chapter takes in its subsections. Headings
+/// match case-insensitively, and sections come out in document order.
+pub fn heading_sections(
+    content: &[u8],
+    prices: &[(String, Amount)],
+) -> OpsResult<Vec<ContentSection>> {
+    let text = std::str::from_utf8(content)
+        .map_err(|_| OpsError::invalid_operation("only text content has headings"))?;
+    let headings = heading_ranges(text);
+
+    let mut sections = Vec::with_capacity(prices.len());
+    for (title, price) in prices {
+        let wanted = title.trim().to_lowercase();
+        let (heading, range) = headings
+            .iter()
+            .find(|(heading, _)| heading.to_lowercase() == wanted)
+            .ok_or_else(|| OpsError::invalid_operation(format!("no heading \"{}\"", title)))?;
+        sections.push(ContentSection::new(
+            *heading,
+            content,
+            range.clone(),
+            *price,
+        ));
+    }
+    sections.sort_by_key(|section| section.offset);
+    Ok(sections)
+}
+
+/// Every ATX heading of `text` with the byte range of its section.
+fn heading_ranges(text: &str) -> Vec<(&str, Range<usize>)> {
+    let mut starts: Vec<(usize, usize, &str)> = Vec::new();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if let Some((level, heading)) = markdown_heading(line) {
+            starts.push((offset, level, heading));
+        }
+        offset += line.len();
+    }
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, &(start, level, heading))| {
+            let end = starts[i + 1..]
+                .iter()
+                .find(|&&(_, next_level, _)| next_level <= level)
+                .map_or(text.len(), |&(next_start, _, _)| next_start);
+            (heading, start..end)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::NodeStateConfig;
+    use nodalync_types::{Metadata, Visibility};
+    use tempfile::TempDir;
+
+    const REPORT: &str =
+        "# Report\nOverview\n## Methods\nHow\n### Setup\nRig\n## Results\nNumbers\n";
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let config = NodeStateConfig::new(temp_dir.path());
+        let state = nodalync_store::NodeState::open(config).unwrap();
+
+        let (_, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+
+        let ops = DefaultNodeOperations::with_defaults(state, peer_id);
+        (ops, temp_dir)
+    }
+
+    #[test]
+    fn test_heading_sections() {
+        let content = REPORT.as_bytes();
+        let sections = heading_sections(
+            content,
+            &[("results".to_string(), 30), ("Methods".to_string(), 20)],
+        )
+        .unwrap();
+
+        // In document order, with subsections included
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].title, "Methods");
+        assert_eq!(
+            &REPORT[sections[0].range()],
+            "## Methods\nHow\n### Setup\nRig\n"
+        );
+        assert_eq!(&REPORT[sections[1].range()], "## Results\nNumbers\n");
+        assert_eq!(sections[1].price, 30);
+        assert!(sections[1].verify(&content[sections[1].range()]));
+
+        // A heading that isn't there
+        assert!(heading_sections(content, &[("Appendix".to_string(), 1)]).is_err());
+        // Binary content has no headings
+        assert!(heading_sections(&[0xff, 0xfe], &[("Methods".to_string(), 1)]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_set_section_prices() {
+        let (ops, _temp) = create_test_ops();
+        let content = REPORT.as_bytes();
+        let hash = ops
+            .create_content(content, Metadata::new("Report", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 100)
+            .await
+            .unwrap();
+
+        ops.set_section_prices(&hash, &[("Results".to_string(), 25)])
+            .unwrap();
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        assert_eq!(manifest.metadata.sections.len(), 1);
+        assert_eq!(manifest.metadata.sections[0].price, 25);
+
+        // Own sections are read locally, without payment
+        let response = ops.query_section(&hash, 0, 0).await.unwrap();
+        assert_eq!(response.content, b"## Results\nNumbers\n");
+        assert_eq!(response.receipt.amount, 0);
+        assert!(matches!(
+            ops.query_section(&hash, 1, 0).await,
+            Err(OpsError::SectionNotFound { index: 1, .. })
+        ));
+
+        // A chapter and its subsection overlap
+        assert!(ops
+            .set_section_prices(
+                &hash,
+                &[("Methods".to_string(), 10), ("Setup".to_string(), 5)]
+            )
+            .is_err());
+
+        // Sections built from other content don't verify
+        let other = ContentSection::new("Results", b"different bytes", 0..9, 10);
+        assert!(ops.set_content_sections(&hash, vec![other]).is_err());
+
+        ops.set_content_sections(&hash, Vec::new()).unwrap();
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        assert!(manifest.metadata.sections.is_empty());
+    }
+}
@@ -9,9 +9,10 @@ use nodalync_store::SettlementQueueStore;
 use nodalync_types::Payment;
 use nodalync_valid::Validator;
 use nodalync_wire::SettleConfirmPayload;
-use tracing::{info, warn};
+use tracing::{info, instrument, warn};
 
 use crate::error::OpsResult;
+use crate::events::OpsEvent;
 use crate::extraction::L1Extractor;
 use crate::node_ops::{current_timestamp, NodeOperations};
 
@@ -31,7 +32,8 @@ where
     /// 6. Updates last_settlement_time
     ///
     /// Returns the batch ID if settlement was triggered, None otherwise.
-    pub async fn trigger_settlement_batch(&mut self) -> OpsResult<Option<Hash>> {
+    #[instrument(name = "settle_batch", skip_all)]
+    pub async fn trigger_settlement_batch(&self) -> OpsResult<Option<Hash>> {
         let timestamp = current_timestamp();
 
         // Get pending total and last settlement time
@@ -48,6 +50,12 @@ where
             return Ok(None);
         }
 
+        // Nodes sharing a queue take turns, so a batch is settled once
+        let Some(_batch_lock) = self.state.settlement.try_lock_batch()? else {
+            info!("Another node is settling the shared queue");
+            return Ok(None);
+        };
+
         // 2. Get pending distributions from queue
         let pending = self.state.settlement.get_pending()?;
         if pending.is_empty() {
@@ -82,16 +90,19 @@ where
             match settlement.settle_batch(&batch).await {
                 Ok(tx_id) => {
                     info!(batch_id = %batch_id, tx_id = %tx_id, "Batch settled on-chain");
+                    self.record_settlement_transaction(tx_id.as_str(), batch.total_amount());
                     tx_id.to_string()
                 }
                 Err(e) => {
                     warn!(batch_id = %batch_id, error = %e, "On-chain settlement failed, keeping queue intact");
+                    self.record_settlement_failure(batch_id, batch.total_amount(), &e.to_string());
                     return Err(crate::error::OpsError::SettlementFailed(e.to_string()));
                 }
             }
         } else {
             format!("local-{}", batch_id) // No settlement configured
         };
+        self.record_settlement_payout(&transaction_id, &pending);
 
         // 5. Broadcast settlement confirmation (if network available)
         if let Some(network) = self.network().cloned() {
@@ -114,6 +125,12 @@ where
         // 7. Update last_settlement_time
         self.state.settlement.set_last_settlement_time(timestamp)?;
 
+        self.emit(OpsEvent::SettlementConfirmed {
+            batch_id,
+            transaction_id,
+            amount: batch.total_amount(),
+        });
+
         Ok(Some(batch_id))
     }
 
@@ -137,9 +154,14 @@ where
     }
 
     /// Force settlement regardless of threshold/interval.
-    pub async fn force_settlement(&mut self) -> OpsResult<Option<Hash>> {
+    pub async fn force_settlement(&self) -> OpsResult<Option<Hash>> {
         let timestamp = current_timestamp();
 
+        let Some(_batch_lock) = self.state.settlement.try_lock_batch()? else {
+            info!("Another node is settling the shared queue");
+            return Ok(None);
+        };
+
         // Get pending distributions
         let pending = self.state.settlement.get_pending()?;
         if pending.is_empty() {
@@ -172,16 +194,19 @@ where
             match settlement.settle_batch(&batch).await {
                 Ok(tx_id) => {
                     info!(batch_id = %batch_id, tx_id = %tx_id, "Force batch settled on-chain");
+                    self.record_settlement_transaction(tx_id.as_str(), batch.total_amount());
                     tx_id.to_string()
                 }
                 Err(e) => {
                     warn!(batch_id = %batch_id, error = %e, "On-chain force settlement failed, keeping queue intact");
+                    self.record_settlement_failure(batch_id, batch.total_amount(), &e.to_string());
                     return Err(crate::error::OpsError::SettlementFailed(e.to_string()));
                 }
             }
         } else {
             format!("local-force-{}", batch_id) // No settlement configured
         };
+        self.record_settlement_payout(&transaction_id, &pending);
 
         // Broadcast settlement confirmation (if network available)
         if let Some(network) = self.network().cloned() {
@@ -234,7 +259,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_trigger_settlement_empty() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // No pending distributions, should return None
         let result = ops.trigger_settlement_batch().await.unwrap();
@@ -243,7 +268,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_force_settlement() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Add some distributions to the queue
         let dist1 = QueuedDistribution::new(
@@ -275,7 +300,7 @@ mod tests {
 
     #[test]
     fn test_get_pending_total() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Initially zero
         assert_eq!(ops.get_pending_settlement_total().unwrap(), 0);
@@ -296,7 +321,7 @@ mod tests {
 
     #[test]
     fn test_should_trigger_settlement() {
-        let (mut ops, _temp) = create_test_ops();
+        let (ops, _temp) = create_test_ops();
 
         // Set a recent last_settlement_time so interval trigger doesn't fire
         let recent_time = current_timestamp();
@@ -366,7 +391,7 @@ mod tests {
     async fn test_trigger_settlement_with_mock_settlement() {
         use nodalync_test_utils::*;
 
-        let (mut ops, _mock_net, mock_settle, _temp) = create_test_ops_with_mocks();
+        let (ops, _mock_net, mock_settle, _temp) = create_test_ops_with_mocks();
 
         // Enqueue distributions
         let dist1 = QueuedDistribution::new(
@@ -408,7 +433,7 @@ mod tests {
     async fn test_trigger_settlement_below_threshold_no_action() {
         use nodalync_test_utils::*;
 
-        let (mut ops, _mock_net, mock_settle, _temp) = create_test_ops_with_mocks();
+        let (ops, _mock_net, mock_settle, _temp) = create_test_ops_with_mocks();
 
         // Set a recent last_settlement_time so interval trigger doesn't fire
         let recent_time = current_timestamp();
@@ -444,7 +469,7 @@ mod tests {
         use nodalync_test_utils::*;
 
         let mock_settle = MockSettlement::new().with_balance(10000);
-        let (mut ops, _temp) =
+        let (ops, _temp) =
             create_test_ops_with_settlement(std::sync::Arc::new(mock_settle.clone()));
 
         // Enqueue a distribution
@@ -464,13 +489,21 @@ mod tests {
         // Verify the mock received the batch
         let batches = mock_settle.settled_batches();
         assert_eq!(batches.len(), 1);
+
+        // The batch transaction should appear in the wallet history
+        let history = ops.recent_wallet_transactions(10).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(
+            history[0].kind,
+            nodalync_store::WalletTransactionKind::Settlement
+        );
     }
 
     #[tokio::test]
     async fn test_settlement_broadcasts_confirm() {
         use nodalync_test_utils::*;
 
-        let (mut ops, mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+        let (ops, mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
 
         // Enqueue a distribution
         let dist = QueuedDistribution::new(
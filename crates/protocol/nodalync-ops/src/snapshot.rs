@@ -0,0 +1,448 @@
+//! State snapshots and fast sync.
+//!
+//! A fresh node otherwise learns about content and peers only as gossip
+//! reaches it. A well-connected node with
+//! [`SnapshotConfig::serve`](crate::SnapshotConfig::serve) set answers
+//! snapshot requests with its most recent announcements and best-known
+//! peers, signed as a whole. [`fast_sync`](NodeOperations::fast_sync)
+//! fetches one, checks it came from the peer asked, and imports it.
+//!
+//! A snapshot is only as trustworthy as its issuer: each announcement is
+//! still checked against its own publisher signature, and peer records
+//! never overwrite peers we already know.
+
+use std::collections::HashSet;
+
+use nodalync_crypto::{peer_id_from_public_key, PeerId, Signature, Timestamp};
+use nodalync_net::Multiaddr;
+use nodalync_store::{PeerInfo, PeerStore};
+use nodalync_types::MAX_CLOCK_SKEW_MS;
+use nodalync_valid::{sign_snapshot, validate_snapshot, Validator};
+use nodalync_wire::{SnapshotPeer, SnapshotRequestPayload, SnapshotResponsePayload, StateSnapshot};
+use tracing::{debug, info};
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::NodeOperations;
+
+/// What importing a snapshot did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotImport {
+    /// The node that took the snapshot.
+    pub issuer: PeerId,
+    /// When it was taken.
+    pub created_at: Timestamp,
+    /// Announcements in the snapshot.
+    pub announcements: usize,
+    /// Announcements stored (new, validly signed, not withdrawn).
+    pub announcements_stored: usize,
+    /// Peer records in the snapshot.
+    pub peers: usize,
+    /// Peers we didn't know before.
+    pub peers_added: usize,
+    /// New peers dialed.
+    pub dialed: usize,
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Take a signed snapshot of our announcement index and peer list.
+    ///
+    /// Holds up to `max_announcements` of the most recently received
+    /// announcements and `max_peers` of the most recently seen peers,
+    /// leaving out revoked keys.
+    pub fn export_snapshot(
+        &self,
+        max_announcements: usize,
+        max_peers: usize,
+    ) -> OpsResult<StateSnapshot> {
+        let private_key = self.private_key().ok_or(OpsError::PrivateKeyRequired)?;
+
+        let mut announcements = self.state.list_announcements();
+        announcements.truncate(max_announcements);
+
+        let mut known = self.state.peers.list()?;
+        known.retain(|peer| {
+            peer.public_key.0 != [0u8; 32]
+                && peer.peer_id != self.peer_id()
+                && !self.is_peer_revoked(&peer.peer_id)
+        });
+        known.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+        known.truncate(max_peers);
+        let peers = known
+            .into_iter()
+            .map(|peer| SnapshotPeer {
+                libp2p_peer_id: self
+                    .network()
+                    .and_then(|network| network.libp2p_peer_id(&peer.peer_id))
+                    .map(|id| id.to_string()),
+                peer_id: peer.peer_id,
+                public_key: peer.public_key,
+                addresses: peer.addresses,
+                last_seen: peer.last_seen,
+            })
+            .collect();
+
+        let mut snapshot = StateSnapshot {
+            issuer: self.peer_id(),
+            issuer_key: private_key.public_key(),
+            created_at: self.network_time(),
+            announcements,
+            peers,
+            signature: Signature::from_bytes([0u8; 64]),
+        };
+        sign_snapshot(private_key, &mut snapshot)?;
+        Ok(snapshot)
+    }
+
+    /// Handle an incoming snapshot request.
+    ///
+    /// Answers with no snapshot unless serving is enabled. The requester's
+    /// limits apply within our own.
+    pub(crate) fn handle_snapshot_request(
+        &self,
+        requester: &PeerId,
+        request: &SnapshotRequestPayload,
+    ) -> OpsResult<SnapshotResponsePayload> {
+        let config = &self.config.snapshot;
+        if !config.serve {
+            debug!(requester = %requester, "Not serving snapshots");
+            return Ok(SnapshotResponsePayload { snapshot: None });
+        }
+
+        let snapshot = self.export_snapshot(
+            config
+                .max_announcements
+                .min(request.max_announcements as usize),
+            config.max_peers.min(request.max_peers as usize),
+        )?;
+        debug!(
+            requester = %requester,
+            announcements = snapshot.announcements.len(),
+            peers = snapshot.peers.len(),
+            "Answered snapshot request"
+        );
+        Ok(SnapshotResponsePayload {
+            snapshot: Some(snapshot),
+        })
+    }
+
+    /// Import a snapshot's announcements and peers.
+    ///
+    /// The snapshot must be validly signed by a key that isn't revoked and
+    /// be no older than
+    /// [`SnapshotConfig::max_age_ms`](crate::SnapshotConfig::max_age_ms).
+    /// Announcements we already hold are kept as they are; the rest go
+    /// through the same checks as gossiped ones. Only unknown peers are
+    /// added, and only if their ID matches their key.
+    pub fn import_snapshot(&self, snapshot: &StateSnapshot) -> OpsResult<SnapshotImport> {
+        validate_snapshot(snapshot)?;
+        if self.is_peer_revoked(&snapshot.issuer) {
+            return Err(OpsError::invalid_operation(
+                "snapshot issued by a revoked key",
+            ));
+        }
+        let now = self.network_time();
+        if snapshot.created_at > now.saturating_add(MAX_CLOCK_SKEW_MS) {
+            return Err(OpsError::invalid_operation("snapshot is from the future"));
+        }
+        if now.saturating_sub(snapshot.created_at) > self.config.snapshot.max_age_ms {
+            return Err(OpsError::invalid_operation("snapshot is too old"));
+        }
+
+        let mut import = SnapshotImport {
+            issuer: snapshot.issuer,
+            created_at: snapshot.created_at,
+            announcements: snapshot.announcements.len(),
+            announcements_stored: 0,
+            peers: snapshot.peers.len(),
+            peers_added: 0,
+            dialed: 0,
+        };
+
+        let mut seen = HashSet::new();
+        let accepted: Vec<_> = snapshot
+            .announcements
+            .iter()
+            .filter(|announcement| {
+                seen.insert(announcement.hash)
+                    && self.state.get_announcement(&announcement.hash).is_none()
+                    && self.announcement_acceptable(announcement)
+            })
+            .cloned()
+            .collect();
+        import.announcements_stored = self.state.store_announcements(&accepted);
+
+        for peer in &snapshot.peers {
+            if peer.peer_id == self.peer_id()
+                || peer.peer_id != peer_id_from_public_key(&peer.public_key)
+                || self.is_peer_revoked(&peer.peer_id)
+                || self.state.peers.get(&peer.peer_id)?.is_some()
+            {
+                continue;
+            }
+            self.state.peers.upsert(&PeerInfo::new(
+                peer.peer_id,
+                peer.public_key,
+                peer.addresses.clone(),
+                peer.last_seen,
+            ))?;
+            if let (Some(network), Some(libp2p_peer)) = (
+                self.network(),
+                peer.libp2p_peer_id
+                    .as_deref()
+                    .and_then(|id| id.parse().ok()),
+            ) {
+                network.register_peer_mapping(libp2p_peer, peer.peer_id);
+            }
+            import.peers_added += 1;
+        }
+
+        info!(
+            issuer = %import.issuer,
+            announcements = import.announcements_stored,
+            peers = import.peers_added,
+            "Imported snapshot"
+        );
+        Ok(import)
+    }
+
+    /// Fetch a snapshot from a connected peer, import it, and dial some of
+    /// the peers it lists.
+    ///
+    /// If the peer's Nodalync ID is known, the snapshot must be issued by
+    /// it. Up to
+    /// [`SnapshotConfig::dial_peers`](crate::SnapshotConfig::dial_peers)
+    /// newly added peers are dialed, best-effort.
+    pub async fn fast_sync(&self, peer: nodalync_net::PeerId) -> OpsResult<SnapshotImport> {
+        let network = self
+            .network()
+            .cloned()
+            .ok_or_else(|| OpsError::invalid_operation("network required to fast sync"))?;
+        let config = self.config.snapshot.clone();
+
+        let snapshot = network
+            .request_snapshot(
+                peer,
+                SnapshotRequestPayload {
+                    max_announcements: config.max_announcements as u32,
+                    max_peers: config.max_peers as u32,
+                },
+            )
+            .await?
+            .snapshot
+            .ok_or_else(|| OpsError::invalid_operation("peer does not serve snapshots"))?;
+        if let Some(expected) = network.nodalync_peer_id(&peer) {
+            if snapshot.issuer != expected {
+                return Err(OpsError::invalid_operation(
+                    "snapshot issued by a different peer",
+                ));
+            }
+        }
+
+        // Only peers the import added are dialed
+        let mut new_peers: Vec<PeerId> = snapshot
+            .peers
+            .iter()
+            .map(|p| p.peer_id)
+            .filter(|id| matches!(self.state.peers.get(id), Ok(None)))
+            .collect();
+        let mut import = self.import_snapshot(&snapshot)?;
+        new_peers.retain(|id| matches!(self.state.peers.get(id), Ok(Some(_))));
+
+        for record in snapshot
+            .peers
+            .iter()
+            .filter(|p| new_peers.contains(&p.peer_id))
+            .take(config.dial_peers)
+        {
+            let Some(addr) = record
+                .addresses
+                .iter()
+                .find_map(|a| a.parse::<Multiaddr>().ok())
+            else {
+                continue;
+            };
+            match network.dial(addr).await {
+                Ok(()) => import.dialed += 1,
+                Err(e) => {
+                    debug!(peer = %record.peer_id, error = %e, "Failed to dial snapshot peer")
+                }
+            }
+        }
+        Ok(import)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{OpsConfig, SnapshotConfig};
+    use crate::{DefaultNodeOperations, Network};
+    use nodalync_crypto::{content_hash, generate_identity, PrivateKey};
+    use nodalync_store::NodeState;
+    use nodalync_test_utils::MockNetwork;
+    use nodalync_types::{ContentType, L1Summary};
+    use nodalync_valid::sign_announcement;
+    use nodalync_wire::AnnouncePayload;
+    use std::sync::Arc;
+
+    fn create_test_ops(config: OpsConfig) -> DefaultNodeOperations {
+        let state = NodeState::open_in_memory().unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops =
+            DefaultNodeOperations::with_config(state, peer_id_from_public_key(&public_key), config);
+        ops.set_private_key(private_key);
+        ops
+    }
+
+    fn signed_announcement(publisher: &PrivateKey, content: &[u8]) -> AnnouncePayload {
+        let hash = content_hash(content);
+        let mut payload = AnnouncePayload {
+            hash,
+            content_type: ContentType::L0,
+            title: "Field notes".to_string(),
+            l1_summary: L1Summary::empty(hash),
+            price: 100,
+            addresses: vec![],
+            publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
+        };
+        sign_announcement(publisher, &mut payload).unwrap();
+        payload
+    }
+
+    /// A serving node that has heard two announcements and one peer.
+    fn create_serving_ops() -> (DefaultNodeOperations, PeerId) {
+        let ops = create_test_ops(
+            OpsConfig::default().with_snapshot(SnapshotConfig::default().with_serve(true)),
+        );
+        let (publisher, _) = generate_identity();
+        ops.state
+            .store_announcement(signed_announcement(&publisher, b"first"));
+        ops.state
+            .store_announcement(signed_announcement(&publisher, b"second"));
+
+        let (_, public_key) = generate_identity();
+        let peer = peer_id_from_public_key(&public_key);
+        let now = ops.network_time();
+        ops.state
+            .peers
+            .upsert(&PeerInfo::new(
+                peer,
+                public_key,
+                vec!["/ip4/10.0.0.7/tcp/9000".to_string()],
+                now,
+            ))
+            .unwrap();
+        (ops, peer)
+    }
+
+    #[test]
+    fn test_export_and_import_snapshot() {
+        let (server, listed) = create_serving_ops();
+        let response = server
+            .handle_snapshot_request(
+                &listed,
+                &SnapshotRequestPayload {
+                    max_announcements: 1,
+                    max_peers: 10,
+                },
+            )
+            .unwrap();
+        let snapshot = response.snapshot.unwrap();
+        assert_eq!(snapshot.issuer, server.peer_id());
+        assert_eq!(snapshot.announcements.len(), 1);
+        assert_eq!(snapshot.peers.len(), 1);
+
+        let fresh = create_test_ops(OpsConfig::default());
+        let import = fresh.import_snapshot(&snapshot).unwrap();
+        assert_eq!(import.announcements_stored, 1);
+        assert_eq!(import.peers_added, 1);
+        assert!(fresh
+            .state
+            .get_announcement(&snapshot.announcements[0].hash)
+            .is_some());
+        assert!(fresh.state.peers.get(&listed).unwrap().is_some());
+
+        // Importing again adds nothing
+        let again = fresh.import_snapshot(&snapshot).unwrap();
+        assert_eq!((again.announcements_stored, again.peers_added), (0, 0));
+
+        // Tampering breaks the issuer signature
+        let mut tampered = snapshot.clone();
+        tampered.announcements[0].price = 1;
+        assert!(create_test_ops(OpsConfig::default())
+            .import_snapshot(&tampered)
+            .is_err());
+
+        // Stale snapshots are refused
+        let strict = OpsConfig::default().with_snapshot(SnapshotConfig::default().with_max_age(0));
+        let mut stale = server.export_snapshot(10, 10).unwrap();
+        stale.created_at -= 1_000;
+        sign_snapshot(server.private_key().unwrap(), &mut stale).unwrap();
+        assert!(create_test_ops(strict).import_snapshot(&stale).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_not_served_by_default() {
+        let (_, requester) = create_serving_ops();
+        let response = create_test_ops(OpsConfig::default())
+            .handle_snapshot_request(
+                &requester,
+                &SnapshotRequestPayload {
+                    max_announcements: 10,
+                    max_peers: 10,
+                },
+            )
+            .unwrap();
+        assert!(response.snapshot.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fast_sync() {
+        let (server, _) = create_serving_ops();
+        let snapshot = server.export_snapshot(10, 10).unwrap();
+        let server_peer = nodalync_net::PeerId::random();
+
+        let network = Arc::new(
+            MockNetwork::new()
+                .with_snapshot(server_peer, snapshot.clone())
+                .with_peer_mapping(server_peer, server.peer_id()),
+        );
+        let mut fresh = create_test_ops(OpsConfig::default());
+        fresh.set_network(Arc::clone(&network) as Arc<dyn Network>);
+
+        let import = fresh.fast_sync(server_peer).await.unwrap();
+        assert_eq!(import.issuer, server.peer_id());
+        assert_eq!(import.announcements_stored, 2);
+        assert_eq!(import.peers_added, 1);
+        assert_eq!(import.dialed, 1);
+        assert_eq!(
+            network.dialed(),
+            vec!["/ip4/10.0.0.7/tcp/9000".parse::<Multiaddr>().unwrap()]
+        );
+
+        // A snapshot passed on by someone other than its issuer is refused
+        let relayer = nodalync_net::PeerId::random();
+        let network = Arc::new(
+            MockNetwork::new()
+                .with_snapshot(relayer, snapshot)
+                .with_peer_mapping(relayer, peer_id_from_public_key(&generate_identity().1)),
+        );
+        let mut other = create_test_ops(OpsConfig::default());
+        other.set_network(Arc::clone(&network) as Arc<dyn Network>);
+        assert!(other.fast_sync(relayer).await.is_err());
+
+        // So is a peer that has nothing to offer
+        assert!(other
+            .fast_sync(nodalync_net::PeerId::random())
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,389 @@
+//! Standing orders: buying announced content automatically.
+//!
+//! A standing order asks for content filed under a tag, at or below a
+//! price, from publishers with at least a given reputation, optionally
+//! within a total budget. [`run_standing_orders`](NodeOperations::run_standing_orders)
+//! evaluates the orders against announcements received since each was
+//! created:
+//!
+//! - An announcement matches if one of its primary topics, read as a tag,
+//!   is the order's tag or a child of it
+//! - A minimum reputation needs a signed announcement; unknown publishers
+//!   count as reputation 0
+//! - Content we hold or cache, or publish ourselves, is never bought, and
+//!   each hash is bought at most once, by the oldest matching order
+//!
+//! Purchases are recorded in the store, written to the inbox folder if one
+//! is configured, and reported as [`OpsEvent::StandingOrderFilled`].
+//! Failed purchases are retried on the next run.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use nodalync_crypto::Hash;
+use nodalync_store::{
+    CacheStore, ContentStore, PeerStore, StandingOrder, StandingOrderPurchase, StandingOrderStore,
+    StoreError,
+};
+use nodalync_types::{normalize_tag, tag_path, Amount};
+use nodalync_valid::Validator;
+use nodalync_wire::AnnouncePayload;
+use tracing::{debug, info, warn};
+
+use crate::error::{OpsError, OpsResult};
+use crate::events::OpsEvent;
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+/// What a standing order run did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StandingOrderReport {
+    /// Announcements evaluated against the orders.
+    pub evaluated: usize,
+    /// Content bought.
+    pub purchased: Vec<StandingOrderPurchase>,
+    /// Purchases that failed.
+    pub failed: usize,
+}
+
+/// Whether an announcement's primary topics fall under `tag`.
+fn matches_tag(announcement: &AnnouncePayload, tag: &str) -> bool {
+    announcement
+        .l1_summary
+        .primary_topics
+        .iter()
+        .filter_map(|topic| normalize_tag(topic))
+        .any(|topic| tag_path(&topic).contains(&tag))
+}
+
+/// Write bought content to the inbox, returning its path.
+fn write_to_inbox(inbox_dir: &Path, hash: &Hash, content: &[u8]) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(inbox_dir)?;
+    let path = inbox_dir.join(hash.to_string());
+    std::fs::write(&path, content)?;
+    Ok(path)
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Add a standing order buying content filed under `tag` for at most
+    /// `max_price` each.
+    ///
+    /// `min_reputation` is the lowest publisher reputation accepted, and
+    /// `budget` caps the total spent. Only content announced from now on
+    /// is bought.
+    pub fn add_standing_order(
+        &self,
+        tag: &str,
+        max_price: Amount,
+        min_reputation: Option<i64>,
+        budget: Option<Amount>,
+    ) -> OpsResult<StandingOrder> {
+        let tag = normalize_tag(tag)
+            .ok_or_else(|| OpsError::invalid_operation("standing order tag is empty"))?;
+        let id = self.state.standing_orders.create(
+            &tag,
+            max_price,
+            min_reputation,
+            budget,
+            current_timestamp(),
+        )?;
+        self.state
+            .standing_orders
+            .get(id)?
+            .ok_or_else(|| StoreError::StandingOrderNotFound(id).into())
+    }
+
+    /// Standing orders, oldest first.
+    pub fn list_standing_orders(&self) -> OpsResult<Vec<StandingOrder>> {
+        Ok(self.state.standing_orders.list()?)
+    }
+
+    /// Remove a standing order. Returns `false` if there was none.
+    ///
+    /// What the order bought stays in its purchase history.
+    pub fn remove_standing_order(&self, id: u64) -> OpsResult<bool> {
+        Ok(self.state.standing_orders.remove(id)?)
+    }
+
+    /// Content bought by standing orders, newest first, optionally for one
+    /// order.
+    pub fn standing_order_purchases(
+        &self,
+        order_id: Option<u64>,
+        limit: u32,
+    ) -> OpsResult<Vec<StandingOrderPurchase>> {
+        Ok(self.state.standing_orders.purchases(order_id, limit)?)
+    }
+
+    /// Buy announced content matching the standing orders.
+    ///
+    /// Returns `None` when standing orders are disabled. At most
+    /// `max_purchases_per_run` purchases are attempted per run.
+    pub async fn run_standing_orders(&self) -> OpsResult<Option<StandingOrderReport>> {
+        let config = self.config.standing_orders.clone();
+        if !config.enabled {
+            return Ok(None);
+        }
+
+        let mut report = StandingOrderReport::default();
+        let mut evaluated: HashSet<Hash> = HashSet::new();
+        let own_peer_id = self.peer_id();
+
+        'orders: for mut order in self.state.standing_orders.list()? {
+            // Oldest announcements first
+            for announcement in self
+                .state
+                .list_announcements_since(order.created_at)
+                .into_iter()
+                .rev()
+            {
+                if report.purchased.len() + report.failed >= config.max_purchases_per_run {
+                    break 'orders;
+                }
+                let hash = announcement.hash;
+                if evaluated.insert(hash) {
+                    report.evaluated += 1;
+                }
+                if self.state.standing_orders.is_purchased(&hash)?
+                    || self.state.content.exists(&hash)
+                    || self.state.cache.is_cached(&hash)
+                {
+                    continue;
+                }
+                if !matches_tag(&announcement, &order.tag) || !order.can_afford(announcement.price)
+                {
+                    continue;
+                }
+
+                let publisher = match self.validator.validate_announcement(&announcement) {
+                    Ok(publisher) => publisher,
+                    Err(e) => {
+                        debug!(hash = %hash, error = %e, "Skipping announcement with invalid signature");
+                        continue;
+                    }
+                };
+                if publisher == Some(own_peer_id) {
+                    continue;
+                }
+                if let Some(min_reputation) = order.min_reputation {
+                    let Some(publisher) = publisher else {
+                        continue;
+                    };
+                    let reputation = self
+                        .state
+                        .peers
+                        .get(&publisher)?
+                        .map_or(0, |peer| peer.reputation);
+                    if reputation < min_reputation {
+                        continue;
+                    }
+                }
+
+                let response = match self.query_content(&hash, announcement.price, None).await {
+                    Ok(response) => response,
+                    Err(e) => {
+                        warn!(order = order.id, hash = %hash, error = %e, "Standing order purchase failed");
+                        report.failed += 1;
+                        continue;
+                    }
+                };
+
+                let purchase = StandingOrderPurchase {
+                    content_hash: hash,
+                    order_id: order.id,
+                    title: announcement.title.clone(),
+                    amount: response.receipt.amount,
+                    purchased_at: current_timestamp(),
+                };
+                self.state.standing_orders.record_purchase(&purchase)?;
+                order.spent += purchase.amount;
+
+                let path = config.inbox_dir.as_deref().and_then(|inbox_dir| {
+                    match write_to_inbox(inbox_dir, &hash, &response.content) {
+                        Ok(path) => Some(path),
+                        Err(e) => {
+                            warn!(hash = %hash, error = %e, "Failed to write standing order purchase to inbox");
+                            None
+                        }
+                    }
+                });
+
+                info!(
+                    order = order.id,
+                    hash = %hash,
+                    amount = purchase.amount,
+                    "Standing order bought content"
+                );
+                self.emit(OpsEvent::StandingOrderFilled {
+                    order_id: order.id,
+                    content_hash: hash,
+                    amount: purchase.amount,
+                    path,
+                });
+                report.purchased.push(purchase);
+            }
+        }
+
+        Ok(Some(report))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{OpsConfig, StandingOrderConfig};
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_net::Network;
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_test_utils::MockNetwork;
+    use nodalync_types::{ContentType, L1Summary, Metadata, Visibility};
+    use nodalync_wire::{QueryRequestPayload, QueryResponsePayload};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn create_test_ops(config: OpsConfig) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops =
+            DefaultNodeOperations::with_config(state, peer_id_from_public_key(&public_key), config);
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    /// Publish free content on a provider and return its announcement,
+    /// filed under `topic`, and the provider's answer to a query for it.
+    async fn free_content(
+        title: &str,
+        topic: &str,
+        price: Amount,
+    ) -> (AnnouncePayload, QueryResponsePayload) {
+        let (provider, _provider_temp) = create_test_ops(OpsConfig::default());
+        let content = title.as_bytes();
+        let hash = provider
+            .create_content(content, Metadata::new(title, content.len() as u64))
+            .unwrap();
+        provider
+            .publish_content(&hash, Visibility::Shared, 0)
+            .await
+            .unwrap();
+        let request = QueryRequestPayload {
+            hash,
+            query: None,
+            payment: None,
+            version_spec: None,
+            payment_nonce: 0,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        };
+        let response = provider
+            .handle_query_request(&provider.peer_id(), &request)
+            .await
+            .unwrap();
+
+        let mut l1_summary = L1Summary::empty(hash);
+        l1_summary.primary_topics = vec![topic.to_string()];
+        let announcement = AnnouncePayload {
+            hash,
+            content_type: ContentType::L0,
+            title: title.to_string(),
+            l1_summary,
+            price,
+            addresses: vec![],
+            publisher_peer_id: Some(nodalync_net::PeerId::random().to_string()),
+            publisher_key: None,
+            signature: None,
+            delegation: None,
+        };
+        (announcement, response)
+    }
+
+    #[tokio::test]
+    async fn test_add_list_and_remove_standing_orders() {
+        let (ops, _temp) = create_test_ops(OpsConfig::default());
+
+        let order = ops
+            .add_standing_order(" Science / Biology ", 100, Some(5), Some(1_000))
+            .unwrap();
+        assert_eq!(order.tag, "science/biology");
+        assert_eq!(order.spent, 0);
+        assert!(ops.add_standing_order(" / ", 100, None, None).is_err());
+
+        assert_eq!(ops.list_standing_orders().unwrap(), vec![order.clone()]);
+        assert!(ops.remove_standing_order(order.id).unwrap());
+        assert!(!ops.remove_standing_order(order.id).unwrap());
+        assert!(ops.list_standing_orders().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_run_disabled() {
+        let (ops, _temp) = create_test_ops(OpsConfig::default());
+        ops.add_standing_order("science", 100, None, None).unwrap();
+        assert!(ops.run_standing_orders().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_run_buys_matching_content_into_inbox() {
+        let inbox = TempDir::new().unwrap();
+        let config = OpsConfig::default().with_standing_orders(
+            StandingOrderConfig::default()
+                .with_enabled(true)
+                .with_inbox_dir(inbox.path()),
+        );
+        let (mut client, _client_temp) = create_test_ops(config);
+        let order = client.add_standing_order("science", 0, None, None).unwrap();
+
+        let (wanted, response) = free_content("Cell division", "Science/Biology", 0).await;
+        let (other_topic, _) = free_content("Borrow checker", "rust", 0).await;
+        let (too_dear, _) = free_content("Genome atlas", "science", 50).await;
+        let network = Arc::new(
+            MockNetwork::new()
+                .with_dht_entry(wanted.hash, wanted.clone())
+                .with_query_response(wanted.hash, response),
+        );
+        client.set_network(Arc::clone(&network) as Arc<dyn Network>);
+        for announcement in [&wanted, &other_topic, &too_dear] {
+            client.state.store_announcement(announcement.clone());
+        }
+
+        let mut events = client.subscribe_events();
+        let report = client.run_standing_orders().await.unwrap().unwrap();
+        assert_eq!(report.evaluated, 3);
+        assert_eq!(report.failed, 0);
+        assert_eq!(report.purchased.len(), 1);
+        assert_eq!(report.purchased[0].content_hash, wanted.hash);
+        assert_eq!(report.purchased[0].order_id, order.id);
+
+        let path = inbox.path().join(wanted.hash.to_string());
+        assert_eq!(std::fs::read(&path).unwrap(), b"Cell division");
+        assert_eq!(
+            events.try_recv().unwrap(),
+            OpsEvent::StandingOrderFilled {
+                order_id: order.id,
+                content_hash: wanted.hash,
+                amount: 0,
+                path: Some(path),
+            }
+        );
+        assert_eq!(
+            client.standing_order_purchases(Some(order.id), 10).unwrap(),
+            report.purchased
+        );
+
+        // Nothing is bought twice
+        let report = client.run_standing_orders().await.unwrap().unwrap();
+        assert!(report.purchased.is_empty());
+        assert_eq!(network.query_requests().len(), 1);
+    }
+}
@@ -0,0 +1,216 @@
+//! Node statistics.
+//!
+//! [`NodeOperations::stats`] gathers what `nodalync status`, the MCP
+//! `status` tool and dashboards show about a node into one snapshot:
+//! content, storage, peers, channels, settlement, earnings, the manifest
+//! index and uptime.
+
+use nodalync_crypto::PeerId;
+use nodalync_store::{
+    CacheStore, ChannelStore, ContentStore, ManifestFilter, ManifestIndexStats, ManifestStore,
+    PeerStore, SettlementQueueStore,
+};
+use nodalync_types::{Amount, ContentType, Visibility};
+use nodalync_valid::Validator;
+
+use crate::error::OpsResult;
+use crate::extraction::L1Extractor;
+use crate::node_ops::NodeOperations;
+
+/// Counts of locally stored manifests.
+///
+/// Types and visibilities added later only count towards `total`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContentCounts {
+    /// All manifests.
+    pub total: u64,
+    /// Raw sources.
+    pub l0: u64,
+    /// Mention summaries.
+    pub l1: u64,
+    /// Entity graphs.
+    pub l2: u64,
+    /// Derived syntheses.
+    pub l3: u64,
+    /// Collections.
+    pub collections: u64,
+    /// Private content.
+    pub private: u64,
+    /// Unlisted content.
+    pub unlisted: u64,
+    /// Shared content.
+    pub shared: u64,
+    /// Offline content.
+    pub offline: u64,
+}
+
+impl ContentCounts {
+    fn count(&mut self, content_type: ContentType, visibility: Visibility) {
+        self.total += 1;
+        match content_type {
+            ContentType::L0 => self.l0 += 1,
+            ContentType::L1 => self.l1 += 1,
+            ContentType::L2 => self.l2 += 1,
+            ContentType::L3 => self.l3 += 1,
+            ContentType::Collection => self.collections += 1,
+            _ => {}
+        }
+        match visibility {
+            Visibility::Private => self.private += 1,
+            Visibility::Unlisted => self.unlisted += 1,
+            Visibility::Shared => self.shared += 1,
+            Visibility::Offline => self.offline += 1,
+            _ => {}
+        }
+    }
+}
+
+/// Snapshot of a node's state across subsystems.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeStats {
+    /// Our peer ID.
+    pub peer_id: PeerId,
+    /// Seconds since these operations were created.
+    pub uptime_secs: u64,
+    /// Locally stored manifests by type and visibility.
+    pub content: ContentCounts,
+    /// Bytes of content held in the content store.
+    pub content_bytes: u64,
+    /// Bytes of content cached from queries.
+    pub cache_bytes: u64,
+    /// Peers in the peer store.
+    pub known_peers: u64,
+    /// Peers currently connected (0 without a network).
+    pub connected_peers: u64,
+    /// Open payment channels.
+    pub open_channels: u64,
+    /// Our balance locked in open channels.
+    pub channel_balance: Amount,
+    /// Distributions waiting in the settlement queue.
+    pub pending_settlement_count: u64,
+    /// Total amount waiting in the settlement queue.
+    pub pending_settlement_total: Amount,
+    /// Paid queries served across owned content.
+    pub total_queries: u64,
+    /// Revenue recorded across owned content.
+    pub total_earned: Amount,
+    /// Manifest index counters.
+    pub manifest_index: ManifestIndexStats,
+}
+
+impl NodeStats {
+    /// Bytes held in the content store and the cache.
+    pub fn storage_bytes(&self) -> u64 {
+        self.content_bytes.saturating_add(self.cache_bytes)
+    }
+
+    /// Fraction of manifest lookups answered from memory.
+    pub fn cache_hit_rate(&self) -> f64 {
+        self.manifest_index.hit_rate()
+    }
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Gather a snapshot of the node's statistics.
+    pub fn stats(&self) -> OpsResult<NodeStats> {
+        let own_id = self.peer_id();
+        let mut content = ContentCounts::default();
+        let mut content_bytes = 0u64;
+        let mut total_queries = 0u64;
+        let mut total_earned: Amount = 0;
+        for manifest in self.state.manifests.list(ManifestFilter::new())? {
+            content.count(manifest.content_type, manifest.visibility);
+            if let Some(size) = self.state.content.size(&manifest.hash)? {
+                content_bytes = content_bytes.saturating_add(size);
+            }
+            if manifest.owner == own_id {
+                total_queries = total_queries.saturating_add(manifest.economics.total_queries);
+                total_earned = total_earned.saturating_add(manifest.economics.total_revenue);
+            }
+        }
+
+        let channels = self.state.channels.list_open()?;
+        let channel_balance = channels
+            .iter()
+            .map(|(_, channel)| channel.my_balance)
+            .fold(0, Amount::saturating_add);
+
+        Ok(NodeStats {
+            peer_id: own_id,
+            uptime_secs: self.started_at.elapsed().as_secs(),
+            content,
+            content_bytes,
+            cache_bytes: self.state.cache.total_size()?,
+            known_peers: self.state.peers.list()?.len() as u64,
+            connected_peers: self
+                .network()
+                .map_or(0, |network| network.connected_peers().len() as u64),
+            open_channels: channels.len() as u64,
+            channel_balance,
+            pending_settlement_count: self.state.settlement.pending_count()?,
+            pending_settlement_total: self.state.settlement.get_pending_total()?,
+            total_queries,
+            total_earned,
+            manifest_index: self.state.manifests.index_stats(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OpsConfig;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_types::Metadata;
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (_, public_key) = generate_identity();
+        let ops = DefaultNodeOperations::with_config(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default(),
+        );
+        (ops, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_stats_counts_content_and_storage() {
+        let (ops, _temp) = create_test_ops();
+        let empty = ops.stats().unwrap();
+        assert_eq!(empty.peer_id, ops.peer_id());
+        assert_eq!(empty.content, ContentCounts::default());
+        assert_eq!(empty.storage_bytes(), 0);
+        assert_eq!(empty.connected_peers, 0);
+
+        let shared = b"Shared notes";
+        let hash = ops
+            .create_content(shared, Metadata::new("Shared", shared.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 10)
+            .await
+            .unwrap();
+        let private = b"Private draft";
+        ops.create_content(private, Metadata::new("Draft", private.len() as u64))
+            .unwrap();
+
+        let stats = ops.stats().unwrap();
+        assert_eq!(stats.content.total, 2);
+        assert_eq!(stats.content.l0, 2);
+        assert_eq!(stats.content.shared, 1);
+        assert_eq!(stats.content.private, 1);
+        assert_eq!(stats.content_bytes, (shared.len() + private.len()) as u64);
+        assert_eq!(stats.open_channels, 0);
+        assert_eq!(stats.pending_settlement_total, 0);
+        assert_eq!(stats.total_earned, 0);
+        assert!(stats.manifest_index.hits + stats.manifest_index.misses > 0);
+    }
+}
@@ -0,0 +1,256 @@
+//! Publisher storefronts.
+//!
+//! A storefront lists everything a publisher shares, with categories,
+//! prices and featured items, in one signed document stored in the DHT
+//! under the publisher's peer ID. Consumers fetch it to browse a
+//! publisher's catalog without searching for each item.
+//!
+//! - [`build_storefront`](NodeOperations::build_storefront) lists our
+//!   shared content: featured items first, in
+//!   [`StorefrontConfig::featured`] order, then the newest
+//! - Each item is filed under its first tag; the storefront's categories
+//!   are the tags used, alphabetically
+//! - A running node with [`StorefrontConfig::enabled`] set republishes it
+//!   every `refresh_interval_secs`, so it tracks the catalog
+//!
+//! A storefront is only the publisher's listing: queries are still checked
+//! against each item's manifest.
+//!
+//! [`StorefrontConfig::featured`]: crate::StorefrontConfig::featured
+//! [`StorefrontConfig::enabled`]: crate::StorefrontConfig::enabled
+
+use std::collections::BTreeSet;
+
+use nodalync_crypto::{PeerId, Signature};
+use nodalync_store::{ManifestFilter, ManifestStore};
+use nodalync_types::{ContentType, Manifest, Visibility, MAX_CLOCK_SKEW_MS, MAX_STOREFRONT_ITEMS};
+use nodalync_valid::{sign_storefront, validate_storefront, Validator};
+use nodalync_wire::{Storefront, StorefrontItem};
+use tracing::{debug, info};
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::NodeOperations;
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Build and sign our storefront from the content we share.
+    ///
+    /// Lists up to `MAX_STOREFRONT_ITEMS` of our shared L0, L1 and L3
+    /// content. Featured hashes that aren't shared are left out.
+    pub fn build_storefront(&self) -> OpsResult<Storefront> {
+        let private_key = self.private_key().ok_or(OpsError::PrivateKeyRequired)?;
+        let config = &self.config.storefront;
+
+        let filter = ManifestFilter::new()
+            .with_owner(self.peer_id())
+            .with_visibility(Visibility::Shared);
+        let mut manifests: Vec<Manifest> = self
+            .state
+            .manifests
+            .list(filter)?
+            .into_iter()
+            .filter(|manifest| manifest.content_type != ContentType::L2)
+            .collect();
+        manifests.sort_by_key(|manifest| {
+            let rank = config
+                .featured
+                .iter()
+                .position(|hash| *hash == manifest.hash)
+                .unwrap_or(usize::MAX);
+            (rank, std::cmp::Reverse(manifest.created_at))
+        });
+        manifests.truncate(MAX_STOREFRONT_ITEMS);
+
+        let items: Vec<StorefrontItem> = manifests
+            .into_iter()
+            .map(|manifest| StorefrontItem {
+                featured: config.featured.contains(&manifest.hash),
+                category: manifest.metadata.tags.first().cloned(),
+                hash: manifest.hash,
+                title: manifest.metadata.title,
+                content_type: manifest.content_type,
+                price: manifest.economics.price,
+            })
+            .collect();
+        let categories: BTreeSet<String> = items
+            .iter()
+            .filter_map(|item| item.category.clone())
+            .collect();
+
+        let mut storefront = Storefront {
+            publisher: self.peer_id(),
+            publisher_key: private_key.public_key(),
+            name: config.name.clone(),
+            created_at: self.network_time(),
+            categories: categories.into_iter().collect(),
+            items,
+            signature: Signature::from_bytes([0u8; 64]),
+        };
+        sign_storefront(private_key, &mut storefront)?;
+        Ok(storefront)
+    }
+
+    /// Regenerate our storefront and store it in the DHT.
+    pub async fn publish_storefront(&self) -> OpsResult<Storefront> {
+        let network = self
+            .network()
+            .cloned()
+            .ok_or_else(|| OpsError::invalid_operation("network required to publish storefront"))?;
+
+        let storefront = self.build_storefront()?;
+        network.dht_put_storefront(storefront.clone()).await?;
+        info!(
+            items = storefront.items.len(),
+            categories = storefront.categories.len(),
+            "Published storefront"
+        );
+        Ok(storefront)
+    }
+
+    /// Fetch a publisher's storefront from the DHT.
+    ///
+    /// Returns `None` if the publisher has none. The storefront must be
+    /// validly signed by the publisher, whose key isn't revoked, and be no
+    /// older than
+    /// [`StorefrontConfig::max_age_ms`](crate::StorefrontConfig::max_age_ms).
+    pub async fn fetch_storefront(&self, publisher: &PeerId) -> OpsResult<Option<Storefront>> {
+        let network = self
+            .network()
+            .cloned()
+            .ok_or_else(|| OpsError::invalid_operation("network required to fetch storefront"))?;
+
+        let Some(storefront) = network.dht_get_storefront(publisher).await? else {
+            debug!(publisher = %publisher, "No storefront found");
+            return Ok(None);
+        };
+        self.check_storefront(publisher, &storefront)?;
+        Ok(Some(storefront))
+    }
+
+    /// Check a fetched storefront is the publisher's, signed and current.
+    fn check_storefront(&self, publisher: &PeerId, storefront: &Storefront) -> OpsResult<()> {
+        validate_storefront(storefront)?;
+        if storefront.publisher != *publisher {
+            return Err(OpsError::invalid_operation(
+                "storefront published by a different peer",
+            ));
+        }
+        if self.is_peer_revoked(publisher) {
+            return Err(OpsError::invalid_operation(
+                "storefront published by a revoked key",
+            ));
+        }
+
+        let now = self.network_time();
+        if storefront.created_at > now.saturating_add(MAX_CLOCK_SKEW_MS) {
+            return Err(OpsError::invalid_operation("storefront is from the future"));
+        }
+        if now.saturating_sub(storefront.created_at) > self.config.storefront.max_age_ms {
+            return Err(OpsError::invalid_operation("storefront is too old"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{OpsConfig, StorefrontConfig};
+    use crate::{DefaultNodeOperations, Network};
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key, Hash};
+    use nodalync_store::NodeState;
+    use nodalync_test_utils::MockNetwork;
+    use nodalync_types::Metadata;
+    use std::sync::Arc;
+
+    fn create_test_ops(config: OpsConfig) -> (DefaultNodeOperations, MockNetwork) {
+        let state = NodeState::open_in_memory().unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops =
+            DefaultNodeOperations::with_config(state, peer_id_from_public_key(&public_key), config);
+        ops.set_private_key(private_key);
+        let network = MockNetwork::new();
+        ops.set_network(Arc::new(network.clone()) as Arc<dyn Network>);
+        (ops, network)
+    }
+
+    async fn publish(
+        ops: &DefaultNodeOperations,
+        title: &str,
+        tags: &[&str],
+        visibility: Visibility,
+    ) -> Hash {
+        let content = format!("Content of {}", title);
+        let metadata = Metadata::new(title, content.len() as u64)
+            .with_tags(tags.iter().map(|tag| tag.to_string()).collect());
+        let hash = ops.create_content(content.as_bytes(), metadata).unwrap();
+        ops.publish_content(&hash, visibility, 100).await.unwrap();
+        hash
+    }
+
+    #[tokio::test]
+    async fn test_publish_and_fetch_storefront() {
+        let config = OpsConfig::default()
+            .with_storefront(StorefrontConfig::default().with_name("Field Guides"));
+        let (mut ops, network) = create_test_ops(config);
+        let owls = publish(&ops, "Owls", &["birds"], Visibility::Shared).await;
+        publish(&ops, "Moss", &["plants", "birds"], Visibility::Shared).await;
+        let hidden = publish(&ops, "Draft", &[], Visibility::Private).await;
+        ops.config.storefront.featured = vec![owls, hidden];
+
+        let published = ops.publish_storefront().await.unwrap();
+        assert_eq!(published.name, "Field Guides");
+        assert_eq!(published.items.len(), 2);
+        assert_eq!(published.categories, vec!["birds", "plants"]);
+        // The featured item comes first; private content isn't listed
+        assert_eq!(published.items[0].hash, owls);
+        assert!(published.items[0].featured);
+        assert_eq!(published.items[0].price, 100);
+        assert_eq!(published.featured().count(), 1);
+        assert_eq!(published.in_category("plants").count(), 1);
+        assert!(network.storefronts().contains_key(&ops.peer_id()));
+
+        let fetched = ops.fetch_storefront(&ops.peer_id()).await.unwrap();
+        assert_eq!(fetched, Some(published));
+
+        let (_, other_key) = generate_identity();
+        let stranger = peer_id_from_public_key(&other_key);
+        assert_eq!(ops.fetch_storefront(&stranger).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rejects_bad_storefronts() {
+        let (ops, _) = create_test_ops(OpsConfig::default());
+        publish(&ops, "Owls", &["birds"], Visibility::Shared).await;
+        let storefront = ops.build_storefront().unwrap();
+
+        // Stored under someone else's ID
+        let (_, other_key) = generate_identity();
+        let other = peer_id_from_public_key(&other_key);
+        let network = MockNetwork::new().with_storefront(Storefront {
+            publisher: other,
+            ..storefront.clone()
+        });
+        let (mut consumer, _) = create_test_ops(OpsConfig::default());
+        consumer.set_network(Arc::new(network) as Arc<dyn Network>);
+        assert!(consumer.fetch_storefront(&other).await.is_err());
+
+        // Repriced in transit
+        let mut repriced = storefront.clone();
+        repriced.items[0].price = 1;
+        let network = MockNetwork::new().with_storefront(repriced);
+        consumer.set_network(Arc::new(network) as Arc<dyn Network>);
+        assert!(consumer.fetch_storefront(&ops.peer_id()).await.is_err());
+
+        // Too old
+        let network = MockNetwork::new().with_storefront(storefront);
+        consumer.set_network(Arc::new(network) as Arc<dyn Network>);
+        consumer.config.storefront = StorefrontConfig::default().with_max_age(0);
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert!(consumer.fetch_storefront(&ops.peer_id()).await.is_err());
+    }
+}
@@ -0,0 +1,890 @@
+//! Cross-device sync of identity and channel state.
+//!
+//! One identity can run on several devices. A [`SyncBundle`] carries what
+//! another device needs to act as it: the identity key, open payment
+//! channels with their signed checkpoints, query receipts, and the
+//! settlement accounts of channel peers. Bundles are encrypted under the
+//! owner's password and either written to a file or left with a peer that
+//! holds them ([`SyncConfig::serve`](crate::SyncConfig::serve)) for the
+//! owner's other devices to pull.
+//!
+//! Channels are where two devices can do damage. If both spend from the
+//! same channel state, they sign two different states with the same nonce,
+//! a double spend the counterparty can dispute. Importing a bundle only
+//! moves a channel forward when the newer side's history passed through
+//! the older side's current state, judged by checkpoints or, where those
+//! are missing, by the nonce both devices last agreed on. A channel both
+//! devices advanced is reported as a [`ChannelConflict`] and left as it
+//! is.
+
+use std::collections::HashSet;
+
+use nodalync_crypto::{peer_id_from_public_key, Hash, PeerId, PrivateKey, Timestamp};
+use nodalync_store::{
+    decrypt_with_password, encrypt_with_password, ChannelCheckpoint, ChannelStore, QueryReceipt,
+    SyncStore,
+};
+use nodalync_types::{Channel, MAX_CLOCK_SKEW_MS};
+use nodalync_valid::Validator;
+use nodalync_wire::{
+    EncryptedSyncBundle, SyncPullPayload, SyncPullResponsePayload, SyncPushAckPayload,
+    SyncPushPayload,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+/// Version of the sync bundle format.
+pub const SYNC_BUNDLE_VERSION: u32 = 1;
+
+/// A device's state, as carried encrypted in an [`EncryptedSyncBundle`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SyncBundle {
+    /// Bundle format version.
+    pub version: u32,
+    /// The identity the bundle belongs to.
+    pub peer_id: PeerId,
+    /// The identity's private key.
+    private_key: [u8; 32],
+    /// When the bundle was exported.
+    pub created_at: Timestamp,
+    /// Open payment channels.
+    pub channels: Vec<SyncChannel>,
+    /// Receipts of paid queries.
+    pub receipts: Vec<QueryReceipt>,
+    /// Settlement accounts of channel peers.
+    pub accounts: Vec<SyncAccount>,
+}
+
+impl SyncBundle {
+    /// Decrypt a bundle with the owner's password.
+    ///
+    /// Fails if the password is wrong, the format is unknown, or the key
+    /// inside doesn't belong to the bundle's owner.
+    pub fn open(bundle: &EncryptedSyncBundle, password: &str) -> OpsResult<Self> {
+        let plaintext = decrypt_with_password(&bundle.ciphertext, password)?;
+        let opened: SyncBundle = serde_json::from_slice(&plaintext)
+            .map_err(|e| OpsError::invalid_operation(format!("malformed sync bundle: {}", e)))?;
+        if opened.version != SYNC_BUNDLE_VERSION {
+            return Err(OpsError::invalid_operation(format!(
+                "unsupported sync bundle version {}",
+                opened.version
+            )));
+        }
+        if opened.peer_id != bundle.owner
+            || peer_id_from_public_key(&opened.private_key().public_key()) != opened.peer_id
+        {
+            return Err(OpsError::invalid_operation(
+                "sync bundle key doesn't match its owner",
+            ));
+        }
+        Ok(opened)
+    }
+
+    /// The identity's private key.
+    pub fn private_key(&self) -> PrivateKey {
+        PrivateKey::from_bytes(self.private_key)
+    }
+}
+
+impl std::fmt::Debug for SyncBundle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SyncBundle")
+            .field("version", &self.version)
+            .field("peer_id", &self.peer_id)
+            .field("private_key", &"[REDACTED]")
+            .field("created_at", &self.created_at)
+            .field("channels", &self.channels.len())
+            .field("receipts", &self.receipts.len())
+            .field("accounts", &self.accounts.len())
+            .finish()
+    }
+}
+
+/// A payment channel in a [`SyncBundle`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncChannel {
+    /// The channel, with its pending payments.
+    pub channel: Channel,
+    /// Signed states the device recorded, ordered by nonce.
+    pub checkpoints: Vec<ChannelCheckpoint>,
+}
+
+/// A channel peer's settlement account in a [`SyncBundle`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncAccount {
+    /// The channel peer.
+    pub peer_id: PeerId,
+    /// Its settlement account (e.g. `0.0.12345`).
+    pub account: String,
+}
+
+/// A channel both devices advanced since they last agreed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelConflict {
+    /// The channel peer.
+    pub peer_id: PeerId,
+    /// Our channel.
+    pub channel_id: Hash,
+    /// Our nonce.
+    pub local_nonce: u64,
+    /// The bundle's channel.
+    pub bundle_channel_id: Hash,
+    /// The bundle's nonce.
+    pub bundle_nonce: u64,
+}
+
+/// What importing a sync bundle did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncImport {
+    /// When the bundle was exported.
+    pub created_at: Timestamp,
+    /// Channels we didn't have.
+    pub channels_added: usize,
+    /// Channels moved forward to the bundle's state.
+    pub channels_updated: usize,
+    /// Channels where ours was as new or newer.
+    pub channels_unchanged: usize,
+    /// Channels both devices advanced, left as they are.
+    pub conflicts: Vec<ChannelConflict>,
+    /// Receipts we didn't have.
+    pub receipts_added: usize,
+    /// Settlement accounts registered.
+    pub accounts_registered: usize,
+}
+
+impl SyncImport {
+    /// Whether every channel merged cleanly.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// How a channel from a bundle merges with ours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChannelMerge {
+    Add,
+    FastForward,
+    Keep,
+    Conflict,
+}
+
+/// Decide how `theirs` merges with our channel with the same peer.
+///
+/// `base` is the nonce both devices last agreed on, if known.
+fn merge_channel(
+    local: Option<(&Channel, &[ChannelCheckpoint])>,
+    theirs: &SyncChannel,
+    base: Option<u64>,
+) -> ChannelMerge {
+    let Some((local, local_history)) = local else {
+        return ChannelMerge::Add;
+    };
+    let bundle = &theirs.channel;
+    if local.channel_id != bundle.channel_id {
+        return ChannelMerge::Conflict;
+    }
+    if local.nonce == bundle.nonce {
+        return if same_balances(local, bundle) {
+            ChannelMerge::Keep
+        } else {
+            ChannelMerge::Conflict
+        };
+    }
+
+    let (behind, ahead_history) = if bundle.nonce > local.nonce {
+        (local, theirs.checkpoints.as_slice())
+    } else {
+        (bundle, local_history)
+    };
+    let safe = match ahead_history.iter().find(|c| c.nonce == behind.nonce) {
+        Some(checkpoint) => {
+            checkpoint.my_balance == behind.my_balance
+                && checkpoint.their_balance == behind.their_balance
+        }
+        // No record of that state: safe only if the older side hasn't
+        // moved since the devices last agreed
+        None => base.is_some_and(|base| behind.nonce <= base),
+    };
+    match (safe, bundle.nonce > local.nonce) {
+        (false, _) => ChannelMerge::Conflict,
+        (true, true) => ChannelMerge::FastForward,
+        (true, false) => ChannelMerge::Keep,
+    }
+}
+
+fn same_balances(a: &Channel, b: &Channel) -> bool {
+    a.my_balance == b.my_balance && a.their_balance == b.their_balance
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Export our identity and state as a bundle encrypted under
+    /// `password`.
+    ///
+    /// Holds the identity key, open channels with their pending payments
+    /// and checkpoints, receipts of paid queries (cached here or imported
+    /// earlier), and the settlement accounts of channel peers.
+    pub fn export_sync_bundle(&self, password: &str) -> OpsResult<EncryptedSyncBundle> {
+        let private_key = self.private_key().ok_or(OpsError::PrivateKeyRequired)?;
+
+        let mut channels = Vec::new();
+        let mut accounts = Vec::new();
+        for (peer, _) in self.state.channels.list_open()? {
+            let Some(channel) = self.state.channels.get(&peer)? else {
+                continue;
+            };
+            let checkpoints = self.state.channels.checkpoints(&channel.channel_id)?;
+            if let Some(account) = self
+                .settlement()
+                .and_then(|settlement| settlement.get_account_for_peer(&peer))
+            {
+                accounts.push(SyncAccount {
+                    peer_id: peer,
+                    account: account.to_string(),
+                });
+            }
+            channels.push(SyncChannel {
+                channel,
+                checkpoints,
+            });
+        }
+
+        let mut receipts = self.state.cache.receipts()?;
+        let mut seen: HashSet<Hash> = receipts.iter().map(|r| r.receipt.payment_id).collect();
+        for receipt in self.state.sync.list_receipts()? {
+            if seen.insert(receipt.receipt.payment_id) {
+                receipts.push(receipt);
+            }
+        }
+
+        let bundle = SyncBundle {
+            version: SYNC_BUNDLE_VERSION,
+            peer_id: self.peer_id(),
+            private_key: *private_key.as_bytes(),
+            created_at: current_timestamp(),
+            channels,
+            receipts,
+            accounts,
+        };
+        let plaintext = serde_json::to_vec(&bundle)
+            .map_err(|e| OpsError::invalid_operation(format!("encoding error: {}", e)))?;
+        debug!(
+            channels = bundle.channels.len(),
+            receipts = bundle.receipts.len(),
+            "Exported sync bundle"
+        );
+        Ok(EncryptedSyncBundle {
+            owner: bundle.peer_id,
+            created_at: bundle.created_at,
+            ciphertext: encrypt_with_password(&plaintext, password)?,
+        })
+    }
+
+    /// Decrypt a bundle exported by another device of our identity and
+    /// merge it into our state.
+    ///
+    /// Channels we don't have are added. A channel the bundle advanced is
+    /// moved forward only if we haven't advanced it ourselves since;
+    /// otherwise it is reported as a conflict and left as it is, to be
+    /// resolved with the counterparty (`resync_payment_channel`).
+    /// Receipts are added and unknown settlement accounts registered.
+    pub fn import_sync_bundle(
+        &self,
+        bundle: &EncryptedSyncBundle,
+        password: &str,
+    ) -> OpsResult<SyncImport> {
+        let opened = SyncBundle::open(bundle, password)?;
+        if opened.peer_id != self.peer_id() {
+            return Err(OpsError::invalid_operation(
+                "sync bundle belongs to another identity",
+            ));
+        }
+
+        let mut import = SyncImport {
+            created_at: opened.created_at,
+            channels_added: 0,
+            channels_updated: 0,
+            channels_unchanged: 0,
+            conflicts: Vec::new(),
+            receipts_added: 0,
+            accounts_registered: 0,
+        };
+
+        for theirs in &opened.channels {
+            let peer = theirs.channel.peer_id;
+            let local = self.state.channels.get(&peer)?;
+            let local_history = match &local {
+                Some(channel) => self.state.channels.checkpoints(&channel.channel_id)?,
+                None => Vec::new(),
+            };
+            let base = self
+                .state
+                .sync
+                .get_channel_base(&theirs.channel.channel_id)?;
+
+            match merge_channel(
+                local.as_ref().map(|c| (c, local_history.as_slice())),
+                theirs,
+                base,
+            ) {
+                ChannelMerge::Add => {
+                    self.state.channels.create(&peer, theirs.channel.clone())?;
+                    self.import_channel_history(theirs, 0, &[])?;
+                    import.channels_added += 1;
+                }
+                ChannelMerge::FastForward => {
+                    let local_nonce = local.as_ref().map_or(0, |c| c.nonce);
+                    let held = self.state.channels.get_pending_payments(&peer)?;
+                    self.state.channels.update(&peer, &theirs.channel)?;
+                    self.import_channel_history(theirs, local_nonce, &held)?;
+                    import.channels_updated += 1;
+                }
+                ChannelMerge::Keep => {
+                    if base.is_none_or(|base| theirs.channel.nonce > base) {
+                        self.state
+                            .sync
+                            .set_channel_base(&theirs.channel.channel_id, theirs.channel.nonce)?;
+                    }
+                    import.channels_unchanged += 1;
+                }
+                ChannelMerge::Conflict => {
+                    let local = local.expect("conflicts need a local channel");
+                    warn!(
+                        peer = %peer,
+                        local_nonce = local.nonce,
+                        bundle_nonce = theirs.channel.nonce,
+                        "Channel advanced on both devices"
+                    );
+                    import.conflicts.push(ChannelConflict {
+                        peer_id: peer,
+                        channel_id: local.channel_id,
+                        local_nonce: local.nonce,
+                        bundle_channel_id: theirs.channel.channel_id,
+                        bundle_nonce: theirs.channel.nonce,
+                    });
+                }
+            }
+        }
+
+        let cached: HashSet<Hash> = self
+            .state
+            .cache
+            .receipts()?
+            .iter()
+            .map(|r| r.receipt.payment_id)
+            .collect();
+        for receipt in &opened.receipts {
+            if !cached.contains(&receipt.receipt.payment_id)
+                && self.state.sync.store_receipt(receipt)?
+            {
+                import.receipts_added += 1;
+            }
+        }
+
+        if let Some(settlement) = self.settlement() {
+            for account in &opened.accounts {
+                if settlement.get_account_for_peer(&account.peer_id).is_some() {
+                    continue;
+                }
+                match nodalync_settle::AccountId::from_string(&account.account) {
+                    Ok(account_id) => {
+                        settlement.register_peer_account(&account.peer_id, account_id);
+                        import.accounts_registered += 1;
+                    }
+                    Err(e) => {
+                        debug!(peer = %account.peer_id, error = %e, "Skipped settlement account")
+                    }
+                }
+            }
+        }
+
+        info!(
+            added = import.channels_added,
+            updated = import.channels_updated,
+            conflicts = import.conflicts.len(),
+            receipts = import.receipts_added,
+            "Imported sync bundle"
+        );
+        Ok(import)
+    }
+
+    /// Export a bundle and leave it with `peer` for our other devices.
+    ///
+    /// The peer must hold sync bundles; it keeps only the newest one per
+    /// identity.
+    pub async fn push_sync_bundle(
+        &self,
+        peer: &PeerId,
+        password: &str,
+    ) -> OpsResult<EncryptedSyncBundle> {
+        let network = self
+            .network()
+            .cloned()
+            .ok_or_else(|| OpsError::invalid_operation("network required to push sync bundles"))?;
+        let libp2p_peer = network
+            .libp2p_peer_id(peer)
+            .ok_or(OpsError::PeerIdNotFound)?;
+
+        let bundle = self.export_sync_bundle(password)?;
+        let ack = network
+            .push_sync_bundle(
+                libp2p_peer,
+                SyncPushPayload {
+                    bundle: bundle.clone(),
+                },
+            )
+            .await?;
+        if !ack.stored {
+            return Err(OpsError::invalid_operation(format!(
+                "peer did not store the sync bundle: {}",
+                ack.reason.as_deref().unwrap_or("no reason given")
+            )));
+        }
+        info!(peer = %peer, "Pushed sync bundle");
+        Ok(bundle)
+    }
+
+    /// Fetch the bundle `peer` holds for our identity and import it.
+    pub async fn pull_sync_bundle(&self, peer: &PeerId, password: &str) -> OpsResult<SyncImport> {
+        let network = self
+            .network()
+            .cloned()
+            .ok_or_else(|| OpsError::invalid_operation("network required to pull sync bundles"))?;
+        let libp2p_peer = network
+            .libp2p_peer_id(peer)
+            .ok_or(OpsError::PeerIdNotFound)?;
+
+        let bundle = network
+            .pull_sync_bundle(
+                libp2p_peer,
+                SyncPullPayload {
+                    owner: self.peer_id(),
+                },
+            )
+            .await?
+            .bundle
+            .ok_or_else(|| OpsError::invalid_operation("peer holds no sync bundle for us"))?;
+        if bundle.owner != self.peer_id() {
+            return Err(OpsError::invalid_operation(
+                "peer returned another identity's sync bundle",
+            ));
+        }
+        self.import_sync_bundle(&bundle, password)
+    }
+
+    /// Handle an incoming sync push.
+    ///
+    /// Only held if serving is enabled, the bundle is pushed by its owner,
+    /// fits the size limit, and is newer than the one held.
+    pub(crate) fn handle_sync_push(
+        &self,
+        requester: &PeerId,
+        request: &SyncPushPayload,
+    ) -> OpsResult<SyncPushAckPayload> {
+        let config = &self.config.sync;
+        let bundle = &request.bundle;
+        let refusal = if !config.serve {
+            Some("not holding sync bundles")
+        } else if bundle.owner != *requester {
+            Some("bundle must be pushed by its owner")
+        } else if bundle.ciphertext.len() > config.max_bundle_bytes {
+            Some("bundle too large")
+        } else if bundle.created_at > self.network_time().saturating_add(MAX_CLOCK_SKEW_MS) {
+            Some("bundle is from the future")
+        } else if !self.state.sync.put_bundle(bundle)? {
+            Some("a newer bundle is held")
+        } else {
+            None
+        };
+
+        debug!(requester = %requester, stored = refusal.is_none(), "Handled sync push");
+        Ok(SyncPushAckPayload {
+            stored: refusal.is_none(),
+            reason: refusal.map(str::to_string),
+        })
+    }
+
+    /// Handle an incoming sync pull.
+    ///
+    /// Bundles are only handed to their owner's devices.
+    pub(crate) fn handle_sync_pull(
+        &self,
+        requester: &PeerId,
+        request: &SyncPullPayload,
+    ) -> OpsResult<SyncPullResponsePayload> {
+        if !self.config.sync.serve || request.owner != *requester {
+            return Ok(SyncPullResponsePayload { bundle: None });
+        }
+        Ok(SyncPullResponsePayload {
+            bundle: self.state.sync.get_bundle(&request.owner)?,
+        })
+    }
+
+    /// Store a channel's pending payments and checkpoints from a bundle,
+    /// and record its nonce as agreed.
+    ///
+    /// Only checkpoints above `from_nonce` and payments not in `held` are
+    /// stored.
+    fn import_channel_history(
+        &self,
+        theirs: &SyncChannel,
+        from_nonce: u64,
+        held: &[nodalync_types::Payment],
+    ) -> OpsResult<()> {
+        let channel = &theirs.channel;
+        for payment in &channel.pending_payments {
+            if !held.iter().any(|p| p.id == payment.id) {
+                self.state
+                    .channels
+                    .add_payment(&channel.peer_id, payment.clone())?;
+            }
+        }
+        for checkpoint in &theirs.checkpoints {
+            if checkpoint.nonce > from_nonce || from_nonce == 0 {
+                self.state.channels.checkpoint(checkpoint)?;
+            }
+        }
+        self.state
+            .sync
+            .set_channel_base(&channel.channel_id, channel.nonce)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{OpsConfig, SyncConfig};
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, Signature};
+    use nodalync_net::Network;
+    use nodalync_settle::{AccountId, Settlement};
+    use nodalync_store::{CacheStore, CachedContent, NodeState, NodeStateConfig};
+    use nodalync_test_utils::{MockNetwork, MockSettlement};
+    use nodalync_wire::PaymentReceipt;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    const PASSWORD: &str = "correct horse battery staple";
+
+    /// A device running `private_key`'s identity.
+    fn create_device(
+        private_key: &PrivateKey,
+        config: OpsConfig,
+    ) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let peer_id = peer_id_from_public_key(&private_key.public_key());
+        let mut ops = DefaultNodeOperations::with_config(state, peer_id, config);
+        ops.set_private_key(private_key.clone());
+        (ops, temp_dir)
+    }
+
+    fn open_channel(ops: &DefaultNodeOperations, peer: &PeerId) -> Hash {
+        let channel_id = content_hash(&peer.0);
+        ops.accept_payment_channel(&channel_id, peer, 1000, 1000)
+            .unwrap();
+        channel_id
+    }
+
+    /// Pay `amount` over the channel with `peer`, as a query would.
+    fn pay(ops: &DefaultNodeOperations, peer: &PeerId, amount: u64) {
+        let mut channel = ops.state.channels.get(peer).unwrap().unwrap();
+        channel.nonce += 1;
+        channel.my_balance -= amount;
+        channel.their_balance += amount;
+        ops.commit_channel_state(peer, &channel).unwrap();
+    }
+
+    fn nonce(ops: &DefaultNodeOperations, peer: &PeerId) -> u64 {
+        ops.state.channels.get(peer).unwrap().unwrap().nonce
+    }
+
+    #[test]
+    fn test_bundle_roundtrip_to_new_device() {
+        let (private_key, _) = generate_identity();
+        let (laptop, _laptop_dir) = create_device(&private_key, OpsConfig::default());
+        let peer = PeerId::from_bytes([9u8; 20]);
+        open_channel(&laptop, &peer);
+        pay(&laptop, &peer, 100);
+        laptop
+            .state
+            .cache
+            .cache(CachedContent::new(
+                content_hash(b"paid for"),
+                b"paid for".to_vec(),
+                peer,
+                1_000,
+                PaymentReceipt {
+                    payment_id: content_hash(b"payment"),
+                    amount: 100,
+                    timestamp: 1_000,
+                    channel_nonce: 1,
+                    distributor_signature: Signature::from_bytes([0u8; 64]),
+                    app_fee: 0,
+                    app_fee_recipient: None,
+                    referral_fee: 0,
+                    referrer: None,
+                },
+            ))
+            .unwrap();
+
+        let bundle = laptop.export_sync_bundle(PASSWORD).unwrap();
+        assert_eq!(bundle.owner, laptop.peer_id());
+        assert!(SyncBundle::open(&bundle, "wrong password").is_err());
+        let opened = SyncBundle::open(&bundle, PASSWORD).unwrap();
+        assert_eq!(opened.private_key().as_bytes(), private_key.as_bytes());
+        assert_eq!(opened.channels.len(), 1);
+        assert_eq!(opened.channels[0].checkpoints.len(), 1);
+
+        let (phone, _phone_dir) = create_device(&private_key, OpsConfig::default());
+        let import = phone.import_sync_bundle(&bundle, PASSWORD).unwrap();
+        assert_eq!(import.channels_added, 1);
+        assert_eq!(import.receipts_added, 1);
+        assert!(import.is_clean());
+        assert_eq!(nonce(&phone, &peer), 1);
+        assert_eq!(phone.state.sync.list_receipts().unwrap().len(), 1);
+
+        // Importing again changes nothing
+        let again = phone.import_sync_bundle(&bundle, PASSWORD).unwrap();
+        assert_eq!(again.channels_unchanged, 1);
+        assert_eq!(again.receipts_added, 0);
+
+        // Nor does the laptop take its own receipt back
+        let echo = phone.export_sync_bundle(PASSWORD).unwrap();
+        assert_eq!(
+            laptop
+                .import_sync_bundle(&echo, PASSWORD)
+                .unwrap()
+                .receipts_added,
+            0
+        );
+    }
+
+    #[test]
+    fn test_import_rejects_other_identity() {
+        let (laptop_key, _) = generate_identity();
+        let (other_key, _) = generate_identity();
+        let (laptop, _laptop_dir) = create_device(&laptop_key, OpsConfig::default());
+        let (other, _other_dir) = create_device(&other_key, OpsConfig::default());
+
+        let bundle = laptop.export_sync_bundle(PASSWORD).unwrap();
+        assert!(matches!(
+            other.import_sync_bundle(&bundle, PASSWORD),
+            Err(OpsError::InvalidOperation(_))
+        ));
+
+        // A relabelled bundle doesn't open either
+        let mut relabelled = bundle;
+        relabelled.owner = other.peer_id();
+        assert!(SyncBundle::open(&relabelled, PASSWORD).is_err());
+    }
+
+    #[test]
+    fn test_import_fast_forwards_and_keeps_newer() {
+        let (private_key, _) = generate_identity();
+        let (laptop, _laptop_dir) = create_device(&private_key, OpsConfig::default());
+        let (phone, _phone_dir) = create_device(&private_key, OpsConfig::default());
+        let peer = PeerId::from_bytes([9u8; 20]);
+        open_channel(&laptop, &peer);
+        phone
+            .import_sync_bundle(&laptop.export_sync_bundle(PASSWORD).unwrap(), PASSWORD)
+            .unwrap();
+
+        // The laptop spends; the phone catches up
+        pay(&laptop, &peer, 100);
+        pay(&laptop, &peer, 50);
+        let import = phone
+            .import_sync_bundle(&laptop.export_sync_bundle(PASSWORD).unwrap(), PASSWORD)
+            .unwrap();
+        assert_eq!(import.channels_updated, 1);
+        assert!(import.is_clean());
+        let channel = phone.state.channels.get(&peer).unwrap().unwrap();
+        assert_eq!((channel.nonce, channel.my_balance), (2, 850));
+
+        // Now the phone spends; an older laptop bundle leaves it alone
+        let stale = laptop.export_sync_bundle(PASSWORD).unwrap();
+        pay(&phone, &peer, 25);
+        let import = phone.import_sync_bundle(&stale, PASSWORD).unwrap();
+        assert_eq!(import.channels_unchanged, 1);
+        assert!(import.is_clean());
+        assert_eq!(nonce(&phone, &peer), 3);
+    }
+
+    #[test]
+    fn test_import_detects_double_spend() {
+        let (private_key, _) = generate_identity();
+        let (laptop, _laptop_dir) = create_device(&private_key, OpsConfig::default());
+        let (phone, _phone_dir) = create_device(&private_key, OpsConfig::default());
+        let peer = PeerId::from_bytes([9u8; 20]);
+        let channel_id = open_channel(&laptop, &peer);
+        phone
+            .import_sync_bundle(&laptop.export_sync_bundle(PASSWORD).unwrap(), PASSWORD)
+            .unwrap();
+
+        // Both devices spend from the same state
+        pay(&laptop, &peer, 100);
+        pay(&phone, &peer, 30);
+        pay(&phone, &peer, 30);
+
+        let import = phone
+            .import_sync_bundle(&laptop.export_sync_bundle(PASSWORD).unwrap(), PASSWORD)
+            .unwrap();
+        assert!(!import.is_clean());
+        assert_eq!(
+            import.conflicts,
+            vec![ChannelConflict {
+                peer_id: peer,
+                channel_id,
+                local_nonce: 2,
+                bundle_channel_id: channel_id,
+                bundle_nonce: 1,
+            }]
+        );
+        // The phone's channel is untouched
+        let channel = phone.state.channels.get(&peer).unwrap().unwrap();
+        assert_eq!((channel.nonce, channel.my_balance), (2, 940));
+
+        // The same nonce with different balances conflicts too
+        pay(&laptop, &peer, 1);
+        let import = phone
+            .import_sync_bundle(&laptop.export_sync_bundle(PASSWORD).unwrap(), PASSWORD)
+            .unwrap();
+        assert_eq!(import.conflicts.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_falls_back_to_agreed_nonce() {
+        let peer = PeerId::from_bytes([9u8; 20]);
+        let local = Channel::new(content_hash(b"channel"), peer, 1000, 0);
+        let mut ahead = local.clone();
+        ahead.nonce = 4;
+        ahead.my_balance = 900;
+        // The bundle has no record of our state
+        let theirs = SyncChannel {
+            channel: ahead,
+            checkpoints: Vec::new(),
+        };
+        let history: &[ChannelCheckpoint] = &[];
+
+        assert_eq!(
+            merge_channel(Some((&local, history)), &theirs, None),
+            ChannelMerge::Conflict
+        );
+        assert_eq!(
+            merge_channel(Some((&local, history)), &theirs, Some(0)),
+            ChannelMerge::FastForward
+        );
+        assert_eq!(merge_channel(None, &theirs, None), ChannelMerge::Add);
+    }
+
+    #[test]
+    fn test_import_registers_settlement_accounts() {
+        let (private_key, _) = generate_identity();
+        let (mut laptop, _laptop_dir) = create_device(&private_key, OpsConfig::default());
+        let (mut phone, _phone_dir) = create_device(&private_key, OpsConfig::default());
+        let laptop_settlement = Arc::new(MockSettlement::new());
+        let phone_settlement = Arc::new(MockSettlement::new());
+        laptop.set_settlement(laptop_settlement.clone() as Arc<dyn Settlement>);
+        phone.set_settlement(phone_settlement.clone() as Arc<dyn Settlement>);
+
+        let peer = PeerId::from_bytes([9u8; 20]);
+        open_channel(&laptop, &peer);
+        laptop_settlement.register_peer_account(&peer, AccountId::simple(1234));
+
+        let import = phone
+            .import_sync_bundle(&laptop.export_sync_bundle(PASSWORD).unwrap(), PASSWORD)
+            .unwrap();
+        assert_eq!(import.accounts_registered, 1);
+        assert_eq!(
+            phone_settlement.get_account_for_peer(&peer),
+            Some(AccountId::simple(1234))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_push_and_pull_through_peer() {
+        let (private_key, _) = generate_identity();
+        let (mut laptop, _laptop_dir) = create_device(&private_key, OpsConfig::default());
+        let (mut phone, _phone_dir) = create_device(&private_key, OpsConfig::default());
+        let peer = PeerId::from_bytes([9u8; 20]);
+        open_channel(&laptop, &peer);
+
+        let mailbox = PeerId::from_bytes([5u8; 20]);
+        let network = Arc::new(MockNetwork::new());
+        network.register_peer_mapping(nodalync_net::PeerId::random(), mailbox);
+        laptop.set_network(Arc::clone(&network) as Arc<dyn Network>);
+        phone.set_network(Arc::clone(&network) as Arc<dyn Network>);
+
+        assert!(phone.pull_sync_bundle(&mailbox, PASSWORD).await.is_err());
+        let pushed = laptop.push_sync_bundle(&mailbox, PASSWORD).await.unwrap();
+        assert_eq!(network.sync_bundle(&laptop.peer_id()), Some(pushed));
+
+        let import = phone.pull_sync_bundle(&mailbox, PASSWORD).await.unwrap();
+        assert_eq!(import.channels_added, 1);
+    }
+
+    #[test]
+    fn test_handle_sync_push_and_pull() {
+        let (owner_key, _) = generate_identity();
+        let (mailbox_key, _) = generate_identity();
+        let (owner, _owner_dir) = create_device(&owner_key, OpsConfig::default());
+        let bundle = owner.export_sync_bundle(PASSWORD).unwrap();
+        let push = SyncPushPayload {
+            bundle: bundle.clone(),
+        };
+        let pull = SyncPullPayload {
+            owner: owner.peer_id(),
+        };
+
+        // Not serving
+        let (closed, _closed_dir) = create_device(&mailbox_key, OpsConfig::default());
+        assert!(
+            !closed
+                .handle_sync_push(&owner.peer_id(), &push)
+                .unwrap()
+                .stored
+        );
+
+        let config = OpsConfig::default().with_sync(SyncConfig::default().with_serve(true));
+        let (mailbox, _mailbox_dir) = create_device(&mailbox_key, config);
+        let stranger = PeerId::from_bytes([4u8; 20]);
+        let ack = mailbox.handle_sync_push(&stranger, &push).unwrap();
+        assert!(!ack.stored);
+        assert!(ack.reason.is_some());
+
+        assert!(
+            mailbox
+                .handle_sync_push(&owner.peer_id(), &push)
+                .unwrap()
+                .stored
+        );
+        // Not newer than the one held
+        assert!(
+            !mailbox
+                .handle_sync_push(&owner.peer_id(), &push)
+                .unwrap()
+                .stored
+        );
+
+        // Only the owner gets it back
+        assert_eq!(
+            mailbox.handle_sync_pull(&stranger, &pull).unwrap().bundle,
+            None
+        );
+        assert_eq!(
+            mailbox
+                .handle_sync_pull(&owner.peer_id(), &pull)
+                .unwrap()
+                .bundle,
+            Some(bundle)
+        );
+    }
+}
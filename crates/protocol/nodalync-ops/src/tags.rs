@@ -0,0 +1,91 @@
+//! Tag operations.
+//!
+//! Tags on local content are normalized when the content is created, and
+//! every tag used is registered in the store's tag registry along with its
+//! ancestors. The registry backs tag listing and the autocomplete offered
+//! by publish dialogs.
+
+use nodalync_store::{TagInfo, TagStore};
+use nodalync_valid::Validator;
+
+use crate::error::OpsResult;
+use crate::extraction::L1Extractor;
+use crate::node_ops::NodeOperations;
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// List registered tags with their content counts, sorted by tag.
+    pub fn list_tags(&self) -> OpsResult<Vec<TagInfo>> {
+        Ok(self.state.tags.list()?)
+    }
+
+    /// Suggest registered tags for a partially typed tag.
+    ///
+    /// See `TagStore::autocomplete`.
+    pub fn autocomplete_tags(&self, prefix: &str, limit: u32) -> OpsResult<Vec<TagInfo>> {
+        Ok(self.state.tags.autocomplete(prefix, limit)?)
+    }
+
+    /// Register the (already normalized) tags of a stored manifest.
+    pub(crate) fn register_tags(&self, tags: &[String]) -> OpsResult<()> {
+        for tag in tags {
+            self.state.tags.register(tag)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_types::Metadata;
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let config = NodeStateConfig::new(temp_dir.path());
+        let state = NodeState::open(config).unwrap();
+        let (_, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+        let ops = DefaultNodeOperations::with_defaults(state, peer_id);
+        (ops, temp_dir)
+    }
+
+    #[test]
+    fn test_created_content_tags_are_normalized_and_registered() {
+        let (ops, _temp) = create_test_ops();
+
+        let metadata = Metadata::new("Cells", 5).with_tags(vec![
+            "Science / Biology".to_string(),
+            "science/biology".to_string(),
+            " ".to_string(),
+        ]);
+        let hash = ops.create_content(b"cells", metadata).unwrap();
+
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        assert_eq!(manifest.metadata.tags, vec!["science/biology"]);
+
+        let tags: Vec<(String, u64)> = ops
+            .list_tags()
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.tag, t.content_count))
+            .collect();
+        assert_eq!(
+            tags,
+            vec![
+                ("science".to_string(), 1),
+                ("science/biology".to_string(), 1)
+            ]
+        );
+
+        let suggested = ops.autocomplete_tags("bio", 5).unwrap();
+        assert_eq!(suggested.len(), 1);
+        assert_eq!(suggested[0].tag, "science/biology");
+    }
+}
@@ -0,0 +1,401 @@
+//! Tenants: serving several end-users from one node.
+//!
+//! A hosted gateway runs one node (one identity, network stack and content
+//! cache) for many end-users. Each is a tenant with its own API key and an
+//! optional budget:
+//!
+//! - API keys are generated here and returned once; only their hash is
+//!   stored. [`authenticate_tenant`](NodeOperations::authenticate_tenant)
+//!   resolves a key to its tenant for the REST/MCP surfaces
+//! - [`query_content_for_tenant`](NodeOperations::query_content_for_tenant)
+//!   checks the tenant's budget before querying and tags the receipt with
+//!   the tenant, charging what was actually paid
+//! - Content we publish can be attributed to a tenant, so the queries it
+//!   earns from count towards that tenant's earnings
+//!
+//! Content cached by one tenant's query is served to the others without
+//! paying again, as for any other cached content.
+
+use nodalync_crypto::{content_hash, Hash};
+use nodalync_store::{ManifestStore, StoreError, Tenant, TenantPurchase, TenantStore};
+use nodalync_types::Amount;
+use nodalync_valid::Validator;
+use nodalync_wire::VersionSpec;
+use rand::RngCore;
+use tracing::debug;
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+use crate::ops::QueryResponse;
+
+/// Prefix of tenant API keys.
+pub const TENANT_KEY_PREFIX: &str = "ndk_";
+
+/// Longest tenant ID accepted.
+pub const MAX_TENANT_ID_LEN: usize = 64;
+
+/// A tenant with what it has spent and earned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TenantReport {
+    /// The tenant, with its budget and spending.
+    pub tenant: Tenant,
+    /// Queries paid for on its behalf.
+    pub purchases: usize,
+    /// Our content attributed to it.
+    pub content: Vec<Hash>,
+    /// Total paid for queries of its content.
+    pub earned: Amount,
+}
+
+/// Check a tenant ID: lowercase letters, digits, `-` and `_`.
+fn validate_tenant_id(id: &str) -> OpsResult<()> {
+    if id.is_empty() || id.len() > MAX_TENANT_ID_LEN {
+        return Err(OpsError::invalid_operation(format!(
+            "tenant ID must be 1 to {MAX_TENANT_ID_LEN} characters"
+        )));
+    }
+    if !id
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err(OpsError::invalid_operation(format!(
+            "tenant ID {id:?} may only contain lowercase letters, digits, '-' and '_'"
+        )));
+    }
+    Ok(())
+}
+
+/// Generate a new API key.
+fn generate_api_key() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{TENANT_KEY_PREFIX}{}", Hash(bytes))
+}
+
+/// The hash an API key is stored under.
+fn api_key_hash(api_key: &str) -> Hash {
+    content_hash(api_key.as_bytes())
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Add a tenant, returning it with its API key.
+    ///
+    /// The key is only returned here; it can be replaced with
+    /// [`rotate_tenant_key`](Self::rotate_tenant_key) but not recovered.
+    pub fn create_tenant(
+        &self,
+        id: &str,
+        name: &str,
+        budget: Option<Amount>,
+    ) -> OpsResult<(Tenant, String)> {
+        validate_tenant_id(id)?;
+        if self.state.tenants.get(id)?.is_some() {
+            return Err(OpsError::invalid_operation(format!(
+                "tenant {id} already exists"
+            )));
+        }
+
+        let tenant = Tenant {
+            id: id.to_string(),
+            name: name.to_string(),
+            budget,
+            spent: 0,
+            created_at: current_timestamp(),
+        };
+        let api_key = generate_api_key();
+        self.state
+            .tenants
+            .create(&tenant, &api_key_hash(&api_key))?;
+        Ok((tenant, api_key))
+    }
+
+    /// The tenant an API key belongs to.
+    pub fn authenticate_tenant(&self, api_key: &str) -> OpsResult<Tenant> {
+        self.state
+            .tenants
+            .by_key(&api_key_hash(api_key))?
+            .ok_or(OpsError::TenantUnauthorized)
+    }
+
+    /// Get a tenant.
+    pub fn get_tenant(&self, id: &str) -> OpsResult<Tenant> {
+        self.state
+            .tenants
+            .get(id)?
+            .ok_or_else(|| StoreError::TenantNotFound(id.to_string()).into())
+    }
+
+    /// Tenants, oldest first.
+    pub fn list_tenants(&self) -> OpsResult<Vec<Tenant>> {
+        Ok(self.state.tenants.list()?)
+    }
+
+    /// Replace a tenant's API key, returning the new one. The old key stops
+    /// working immediately.
+    pub fn rotate_tenant_key(&self, id: &str) -> OpsResult<String> {
+        let api_key = generate_api_key();
+        self.state.tenants.set_key(id, &api_key_hash(&api_key))?;
+        Ok(api_key)
+    }
+
+    /// Set or clear a tenant's budget.
+    pub fn set_tenant_budget(&self, id: &str, budget: Option<Amount>) -> OpsResult<Tenant> {
+        self.state.tenants.set_budget(id, budget)?;
+        self.get_tenant(id)
+    }
+
+    /// Remove a tenant. Returns `false` if there was none.
+    ///
+    /// Its purchases stay on record; its content is no longer attributed.
+    pub fn remove_tenant(&self, id: &str) -> OpsResult<bool> {
+        Ok(self.state.tenants.remove(id)?)
+    }
+
+    /// Queries paid for on behalf of tenants, newest first, optionally for
+    /// one tenant.
+    pub fn tenant_purchases(
+        &self,
+        tenant: Option<&str>,
+        limit: u32,
+    ) -> OpsResult<Vec<TenantPurchase>> {
+        Ok(self.state.tenants.purchases(tenant, limit)?)
+    }
+
+    /// Attribute our content to a tenant, or clear its attribution with
+    /// `None`.
+    pub fn assign_content_to_tenant(&self, hash: &Hash, tenant: Option<&str>) -> OpsResult<()> {
+        let manifest = self
+            .state
+            .manifests
+            .load(hash)?
+            .ok_or(OpsError::ManifestNotFound(*hash))?;
+        if manifest.owner != self.peer_id() {
+            return Err(OpsError::AccessDenied);
+        }
+        if let Some(tenant) = tenant {
+            self.get_tenant(tenant)?;
+        }
+        Ok(self.state.tenants.assign_content(hash, tenant)?)
+    }
+
+    /// A tenant with what it has spent and earned.
+    pub fn tenant_report(&self, id: &str) -> OpsResult<TenantReport> {
+        let tenant = self.get_tenant(id)?;
+        let purchases = self.state.tenants.purchases(Some(id), u32::MAX)?.len();
+        let content = self.state.tenants.content(id)?;
+        let earned = self.state.tenants.earnings(id)?;
+        Ok(TenantReport {
+            tenant,
+            purchases,
+            content,
+            earned,
+        })
+    }
+
+    /// Query content on behalf of a tenant.
+    ///
+    /// Fails with [`OpsError::TenantBudgetExceeded`] if the tenant's budget
+    /// can't cover `payment_amount`. Otherwise the query runs as
+    /// [`query_content`](Self::query_content), and what the receipt says
+    /// was paid is charged to the tenant and tagged with it.
+    pub async fn query_content_for_tenant(
+        &self,
+        tenant_id: &str,
+        hash: &Hash,
+        payment_amount: Amount,
+        version: Option<VersionSpec>,
+    ) -> OpsResult<QueryResponse> {
+        let tenant = self.get_tenant(tenant_id)?;
+        if !tenant.can_afford(payment_amount) {
+            return Err(OpsError::TenantBudgetExceeded {
+                remaining: tenant.remaining_budget().unwrap_or_default(),
+                tenant: tenant.id,
+                required: payment_amount,
+            });
+        }
+
+        let response = self.query_content(hash, payment_amount, version).await?;
+        if response.receipt.amount > 0 {
+            let charged = self.state.tenants.record_purchase(&TenantPurchase {
+                payment_id: response.receipt.payment_id,
+                tenant: tenant.id,
+                content_hash: *hash,
+                amount: response.receipt.amount,
+                purchased_at: response.receipt.timestamp,
+            })?;
+            debug!(
+                tenant = tenant_id,
+                %hash,
+                amount = response.receipt.amount,
+                charged,
+                "Tenant query paid"
+            );
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OpsConfig;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::{AccessKind, AccessLogStore, AccessRecord, AccessRequester};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_types::{Metadata, Visibility};
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops = DefaultNodeOperations::with_config(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default(),
+        );
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    /// Store content owned by another peer locally, as a replica would,
+    /// priced at `price`.
+    fn foreign_content(ops: &DefaultNodeOperations, content: &[u8], price: Amount) -> Hash {
+        let hash = ops
+            .create_content(content, Metadata::new("Foreign", content.len() as u64))
+            .unwrap();
+        let mut manifest = ops.state.manifests.load(&hash).unwrap().unwrap();
+        let (_, other_pk) = generate_identity();
+        manifest.owner = peer_id_from_public_key(&other_pk);
+        manifest.visibility = Visibility::Shared;
+        manifest.economics.price = price;
+        ops.state.manifests.update(&manifest).unwrap();
+        hash
+    }
+
+    #[test]
+    fn test_create_and_authenticate() {
+        let (ops, _temp) = create_test_ops();
+
+        let (tenant, key) = ops.create_tenant("acme", "Acme Corp", Some(100)).unwrap();
+        assert!(key.starts_with(TENANT_KEY_PREFIX));
+        assert_eq!(tenant.budget, Some(100));
+        assert_eq!(ops.authenticate_tenant(&key).unwrap(), tenant);
+        assert!(matches!(
+            ops.authenticate_tenant("ndk_wrong"),
+            Err(OpsError::TenantUnauthorized)
+        ));
+
+        assert!(ops.create_tenant("acme", "Again", None).is_err());
+        assert!(ops.create_tenant("Bad ID", "Bad", None).is_err());
+        assert!(ops.create_tenant("", "Empty", None).is_err());
+
+        // Rotating invalidates the old key
+        let rotated = ops.rotate_tenant_key("acme").unwrap();
+        assert_ne!(rotated, key);
+        assert!(ops.authenticate_tenant(&key).is_err());
+        assert_eq!(ops.authenticate_tenant(&rotated).unwrap().id, "acme");
+
+        let tenant = ops.set_tenant_budget("acme", None).unwrap();
+        assert_eq!(tenant.budget, None);
+        assert!(ops.remove_tenant("acme").unwrap());
+        assert!(ops.authenticate_tenant(&rotated).is_err());
+        assert!(ops.list_tenants().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_query_for_tenant_charges_budget() {
+        let (ops, _temp) = create_test_ops();
+        ops.create_tenant("acme", "Acme Corp", Some(25)).unwrap();
+        ops.create_tenant("globex", "Globex", None).unwrap();
+        let hash = foreign_content(&ops, b"Priced replica content", 10);
+
+        let response = ops
+            .query_content_for_tenant("acme", &hash, 10, None)
+            .await
+            .unwrap();
+        assert_eq!(response.receipt.amount, 10);
+        ops.query_content_for_tenant("acme", &hash, 10, None)
+            .await
+            .unwrap();
+
+        let acme = ops.get_tenant("acme").unwrap();
+        assert_eq!(acme.remaining_budget(), Some(5));
+        let purchases = ops.tenant_purchases(Some("acme"), 10).unwrap();
+        assert!(!purchases.is_empty());
+        assert!(purchases.iter().all(|p| p.tenant == "acme"));
+        assert_eq!(
+            ops.state
+                .tenants
+                .purchase_tenant(&response.receipt.payment_id)
+                .unwrap()
+                .as_deref(),
+            Some("acme")
+        );
+
+        // Over budget: rejected before querying
+        let err = ops
+            .query_content_for_tenant("acme", &hash, 10, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            OpsError::TenantBudgetExceeded {
+                remaining: 5,
+                required: 10,
+                ..
+            }
+        ));
+
+        // Other tenants are unaffected
+        ops.query_content_for_tenant("globex", &hash, 10, None)
+            .await
+            .unwrap();
+        assert_eq!(ops.get_tenant("globex").unwrap().spent, 10);
+
+        assert!(ops
+            .query_content_for_tenant("nobody", &hash, 10, None)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_content_attribution_and_report() {
+        let (ops, _temp) = create_test_ops();
+        ops.create_tenant("acme", "Acme Corp", None).unwrap();
+        let content = b"Acme's published report";
+        let hash = ops
+            .create_content(content, Metadata::new("Report", content.len() as u64))
+            .unwrap();
+
+        assert!(ops.assign_content_to_tenant(&hash, Some("nobody")).is_err());
+        ops.assign_content_to_tenant(&hash, Some("acme")).unwrap();
+        let foreign = foreign_content(&ops, b"Not ours", 0);
+        assert!(matches!(
+            ops.assign_content_to_tenant(&foreign, Some("acme")),
+            Err(OpsError::AccessDenied)
+        ));
+
+        ops.state
+            .access_log
+            .record(&AccessRecord::new(
+                hash,
+                AccessRequester::Peer(nodalync_crypto::PeerId([4u8; 20])),
+                AccessKind::Query,
+                30,
+                current_timestamp(),
+            ))
+            .unwrap();
+
+        let report = ops.tenant_report("acme").unwrap();
+        assert_eq!(report.content, vec![hash]);
+        assert_eq!(report.earned, 30);
+        assert_eq!(report.purchases, 0);
+    }
+}
@@ -0,0 +1,311 @@
+//! Content withdrawal via signed tombstones.
+//!
+//! Unpublishing or deleting content signs a [`Tombstone`] for its hash,
+//! stores it, and broadcasts it on the announcement topic. Peers that
+//! accept a tombstone drop any cached copy, remove the announcement, and
+//! refuse to serve or re-list the hash.
+//!
+//! Only the content owner's tombstone counts: a tombstone must verify
+//! against its owner key, and must come from the content's known owner
+//! (the manifest owner, or the signed announcement's publisher). A
+//! withdrawal is final; the owner can't publish the same hash again.
+
+use nodalync_crypto::{peer_id_from_public_key, Hash, PeerId};
+use nodalync_store::{CacheStore, ManifestStore, TombstoneStore};
+use nodalync_types::{Manifest, Tombstone};
+use nodalync_valid::{sign_tombstone, validate_tombstone, Validator};
+use nodalync_wire::TombstonePayload;
+use tracing::{debug, info, warn};
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Get the tombstone for a content hash, if it has been withdrawn.
+    pub fn get_tombstone(&self, hash: &Hash) -> OpsResult<Option<Tombstone>> {
+        Ok(self.state.tombstones.get(hash)?)
+    }
+
+    /// Sign, store and broadcast a tombstone for our own content.
+    ///
+    /// The broadcast is best-effort; the tombstone is kept locally either
+    /// way.
+    pub(crate) async fn withdraw_content(&self, hash: &Hash) -> OpsResult<Tombstone> {
+        let private_key = self.private_key().ok_or(OpsError::PrivateKeyRequired)?;
+        let mut tombstone = Tombstone::new(
+            *hash,
+            self.peer_id(),
+            private_key.public_key(),
+            current_timestamp(),
+        );
+        sign_tombstone(private_key, &mut tombstone);
+
+        if !self
+            .state
+            .tombstones
+            .store(&tombstone, tombstone.created_at)?
+        {
+            // Already withdrawn: re-broadcast the original
+            if let Some(existing) = self.state.tombstones.get(hash)? {
+                tombstone = existing;
+            }
+        }
+
+        if let Some(network) = self.network() {
+            let payload = TombstonePayload {
+                tombstone: tombstone.clone(),
+            };
+            if let Err(e) = network.broadcast_tombstone(payload).await {
+                warn!(hash = %hash, "Tombstone broadcast failed (content still withdrawn locally): {}", e);
+            }
+        }
+
+        Ok(tombstone)
+    }
+
+    /// Handle a tombstone broadcast by a content owner.
+    ///
+    /// Validates it against the content's known owner, stores it, drops
+    /// any cached copy and removes the announcement. Tombstones for our own
+    /// content are ignored. Returns whether the tombstone was accepted.
+    pub fn handle_tombstone(&self, payload: &TombstonePayload) -> OpsResult<bool> {
+        let tombstone = &payload.tombstone;
+        let hash = tombstone.content_hash;
+
+        let manifest_owner = self.state.manifests.load(&hash)?.map(|m| m.owner);
+        if manifest_owner == Some(self.peer_id()) {
+            debug!(hash = %hash, "Ignoring tombstone for our own content");
+            return Ok(false);
+        }
+
+        let announcement = self.state.get_announcement(&hash);
+        let publisher = announcement
+            .as_ref()
+            .and_then(|a| a.publisher_key.as_ref())
+            .map(peer_id_from_public_key);
+        validate_tombstone(tombstone, manifest_owner.or(publisher).as_ref())?;
+
+        let stored = self
+            .state
+            .tombstones
+            .store(tombstone, current_timestamp())?;
+        if self.state.cache.remove(&hash)? {
+            debug!(hash = %hash, "Dropped cached copy of withdrawn content");
+        }
+        if announcement.is_some() {
+            self.state.remove_announcement(&hash);
+        }
+
+        info!(hash = %hash, owner = %tombstone.owner, "Content withdrawn by its owner");
+        Ok(stored)
+    }
+
+    /// Refuse to serve content its owner has withdrawn.
+    pub(crate) fn ensure_not_withdrawn(&self, manifest: &Manifest) -> OpsResult<()> {
+        if self.is_withdrawn_by(&manifest.hash, Some(&manifest.owner)) {
+            return Err(OpsError::ContentWithdrawn(manifest.hash));
+        }
+        Ok(())
+    }
+
+    /// Check whether `hash` has been withdrawn by `publisher`.
+    ///
+    /// An unknown publisher (an unsigned announcement) can't be told apart
+    /// from the owner, so any tombstone counts.
+    pub(crate) fn is_withdrawn_by(&self, hash: &Hash, publisher: Option<&PeerId>) -> bool {
+        match self.state.tombstones.get(hash) {
+            Ok(Some(tombstone)) => publisher.is_none_or(|p| *p == tombstone.owner),
+            Ok(None) => false,
+            Err(e) => {
+                warn!(hash = %hash, error = %e, "Failed to look up tombstone");
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OpsConfig;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, Signature};
+    use nodalync_store::{CachedContent, NodeState, NodeStateConfig};
+    use nodalync_types::{ContentType, L1Summary, Metadata, Visibility};
+    use nodalync_valid::sign_announcement;
+    use nodalync_wire::{AnnouncePayload, PaymentReceipt, PreviewRequestPayload};
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops = DefaultNodeOperations::with_config(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default(),
+        );
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    /// Publish content and return its tombstone after unpublishing.
+    async fn publish_and_withdraw(owner: &mut DefaultNodeOperations) -> Tombstone {
+        let content = b"Notes to be erased";
+        let hash = owner
+            .create_content(content, Metadata::new("Notes", content.len() as u64))
+            .unwrap();
+        owner
+            .publish_content(&hash, Visibility::Shared, 0)
+            .await
+            .unwrap();
+        owner.unpublish_content(&hash).await.unwrap();
+        owner.get_tombstone(&hash).unwrap().unwrap()
+    }
+
+    /// Cache a queried copy of `hash` and the owner's signed announcement.
+    /// Returns whether the announcement was stored.
+    fn cache_remote_copy(
+        peer: &mut DefaultNodeOperations,
+        owner: &DefaultNodeOperations,
+        hash: Hash,
+    ) -> bool {
+        let mut announcement = AnnouncePayload {
+            hash,
+            content_type: ContentType::L0,
+            title: "Notes".to_string(),
+            l1_summary: L1Summary::empty(hash),
+            price: 0,
+            addresses: vec![],
+            publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
+        };
+        sign_announcement(owner.private_key().unwrap(), &mut announcement).unwrap();
+
+        peer.state
+            .cache
+            .cache(CachedContent::new(
+                hash,
+                b"Notes to be erased".to_vec(),
+                owner.peer_id(),
+                1_000,
+                PaymentReceipt {
+                    payment_id: content_hash(b"payment"),
+                    amount: 0,
+                    timestamp: 1_000,
+                    channel_nonce: 0,
+                    distributor_signature: Signature::from_bytes([0u8; 64]),
+                    app_fee: 0,
+                    app_fee_recipient: None,
+                    referral_fee: 0,
+                    referrer: None,
+                },
+            ))
+            .unwrap();
+        peer.store_verified_announcement(announcement)
+    }
+
+    #[tokio::test]
+    async fn test_unpublish_emits_tombstone() {
+        let (mut owner, _dir) = create_test_ops();
+        let tombstone = publish_and_withdraw(&mut owner).await;
+        let hash = tombstone.content_hash;
+
+        assert_eq!(tombstone.owner, owner.peer_id());
+        assert!(validate_tombstone(&tombstone, Some(&owner.peer_id())).is_ok());
+
+        // Withdrawn content can't be served, even with a capability, or
+        // published again
+        let manifest = owner.state.manifests.load(&hash).unwrap().unwrap();
+        assert!(matches!(
+            owner.ensure_not_withdrawn(&manifest),
+            Err(OpsError::ContentWithdrawn(_))
+        ));
+        let result =
+            owner.handle_preview_request(&PeerId([1u8; 20]), &PreviewRequestPayload { hash });
+        assert!(result.is_err());
+        assert!(matches!(
+            owner.publish_content(&hash, Visibility::Shared, 0).await,
+            Err(OpsError::ContentWithdrawn(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_peer_drops_withdrawn_content() {
+        let (mut owner, _owner_dir) = create_test_ops();
+        let (mut peer, _peer_dir) = create_test_ops();
+        let hash = content_hash(b"Notes to be erased");
+        assert!(cache_remote_copy(&mut peer, &owner, hash));
+
+        let tombstone = publish_and_withdraw(&mut owner).await;
+        assert!(peer
+            .handle_tombstone(&TombstonePayload { tombstone })
+            .unwrap());
+
+        assert!(!peer.is_content_cached(&hash));
+        assert!(peer.state.get_announcement(&hash).is_none());
+        assert!(peer.get_tombstone(&hash).unwrap().is_some());
+
+        // A re-broadcast of the owner's announcement is no longer listed
+        assert!(!cache_remote_copy(&mut peer, &owner, hash));
+        assert!(peer.state.get_announcement(&hash).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_non_owner_tombstone_ignored() {
+        let (owner, _owner_dir) = create_test_ops();
+        let (mut peer, _peer_dir) = create_test_ops();
+        let (griefer, _griefer_dir) = create_test_ops();
+        let hash = content_hash(b"Notes to be erased");
+        assert!(cache_remote_copy(&mut peer, &owner, hash));
+
+        // Validly signed by the griefer, but not the content's publisher
+        let private_key = griefer.private_key().unwrap().clone();
+        let mut tombstone = Tombstone::new(
+            hash,
+            griefer.peer_id(),
+            private_key.public_key(),
+            current_timestamp(),
+        );
+        sign_tombstone(&private_key, &mut tombstone);
+        assert!(peer
+            .handle_tombstone(&TombstonePayload {
+                tombstone: tombstone.clone()
+            })
+            .is_err());
+
+        // Claiming to be the owner without their key fails too
+        tombstone.owner = owner.peer_id();
+        sign_tombstone(&private_key, &mut tombstone);
+        assert!(peer
+            .handle_tombstone(&TombstonePayload { tombstone })
+            .is_err());
+
+        assert!(peer.is_content_cached(&hash));
+        assert!(peer.state.get_announcement(&hash).is_some());
+        assert!(peer.get_tombstone(&hash).unwrap().is_none());
+
+        // Nobody else can withdraw the griefer's own content either
+        let content = b"Griefer notes";
+        let own = griefer
+            .create_content(content, Metadata::new("Mine", content.len() as u64))
+            .unwrap();
+        let foreign = Tombstone::new(
+            own,
+            owner.peer_id(),
+            owner.private_key().unwrap().public_key(),
+            1,
+        );
+        assert!(!griefer
+            .handle_tombstone(&TombstonePayload { tombstone: foreign })
+            .unwrap());
+        assert!(griefer.get_tombstone(&own).unwrap().is_none());
+    }
+}
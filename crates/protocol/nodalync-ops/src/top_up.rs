@@ -0,0 +1,290 @@
+//! Automatic settlement balance top-ups.
+//!
+//! Paid queries fail once the settlement balance runs dry. With
+//! `TopUpConfig::enabled`, the node (or MCP server) calls `check_top_up`
+//! periodically; whenever the balance is below `min_balance` it deposits
+//! `amount` through the `Settlement` trait.
+//!
+//! Top-ups are posted to the ledger as `auto_top_up`, and the daily cap is
+//! enforced against those entries, so it holds across restarts.
+
+use nodalync_store::{
+    LedgerAccount, LedgerEvent, LedgerStore, WalletTransaction, WalletTransactionKind,
+};
+use nodalync_types::Amount;
+use nodalync_valid::Validator;
+use tracing::{debug, info, warn};
+
+use crate::error::{OpsError, OpsResult};
+use crate::events::OpsEvent;
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+/// Window over which `TopUpConfig::daily_cap` applies (24 hours in ms).
+const TOP_UP_WINDOW_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Result of a top-up check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopUpOutcome {
+    /// The balance is at or above the threshold.
+    NotNeeded {
+        /// Current settlement balance.
+        balance: Amount,
+    },
+    /// A top-up was deposited.
+    Deposited {
+        /// Amount deposited (may be less than configured, near the cap).
+        amount: Amount,
+        /// Settlement balance before the deposit.
+        balance: Amount,
+        /// On-chain transaction ID.
+        transaction_id: String,
+    },
+    /// The balance is low but the daily cap has been reached.
+    CapReached {
+        /// Current settlement balance.
+        balance: Amount,
+        /// Top-ups made in the last 24 hours.
+        deposited_today: Amount,
+    },
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Top up the settlement balance if it has fallen below the threshold.
+    ///
+    /// Returns `None` when automatic top-ups are disabled or settlement is
+    /// not configured. Emits `SettlementToppedUp` after a deposit and
+    /// `TopUpCapReached` when the cap stops one.
+    pub async fn check_top_up(&self) -> OpsResult<Option<TopUpOutcome>> {
+        let config = self.config.top_up.clone();
+        if !config.enabled {
+            return Ok(None);
+        }
+        let Some(settlement) = self.settlement().cloned() else {
+            return Ok(None);
+        };
+
+        let balance = settlement
+            .get_balance()
+            .await
+            .map_err(|e| OpsError::SettlementFailed(e.to_string()))?;
+        if balance >= config.min_balance {
+            debug!(balance, "Settlement balance above top-up threshold");
+            return Ok(Some(TopUpOutcome::NotNeeded { balance }));
+        }
+
+        let deposited_today = self.top_ups_today()?;
+        let amount = config
+            .amount
+            .min(config.daily_cap.saturating_sub(deposited_today));
+        if amount == 0 {
+            warn!(
+                balance,
+                deposited_today,
+                daily_cap = config.daily_cap,
+                "Settlement balance low but daily top-up cap reached"
+            );
+            self.emit(OpsEvent::TopUpCapReached {
+                balance,
+                deposited_today,
+                daily_cap: config.daily_cap,
+            });
+            return Ok(Some(TopUpOutcome::CapReached {
+                balance,
+                deposited_today,
+            }));
+        }
+
+        let transaction_id = settlement
+            .deposit(amount)
+            .await
+            .map_err(|e| OpsError::SettlementFailed(e.to_string()))?
+            .to_string();
+        info!(
+            amount,
+            balance,
+            tx_id = %transaction_id,
+            "Topped up settlement balance"
+        );
+
+        self.state
+            .settlement
+            .record_transaction(&WalletTransaction::new(
+                transaction_id.clone(),
+                WalletTransactionKind::Deposit,
+                amount,
+                current_timestamp(),
+            ))?;
+        self.record_ledger_transfer(
+            LedgerEvent::AutoTopUp,
+            &transaction_id,
+            LedgerAccount::External,
+            LedgerAccount::Contract,
+            amount,
+        );
+        self.emit(OpsEvent::SettlementToppedUp {
+            amount,
+            balance: balance.saturating_add(amount),
+        });
+
+        Ok(Some(TopUpOutcome::Deposited {
+            amount,
+            balance,
+            transaction_id,
+        }))
+    }
+
+    /// Total of automatic top-ups in the last 24 hours, from the ledger.
+    pub fn top_ups_today(&self) -> OpsResult<Amount> {
+        let since = current_timestamp().saturating_sub(TOP_UP_WINDOW_MS);
+        Ok(self
+            .state
+            .ledger
+            .entries(Some(LedgerAccount::Contract), Some(since))?
+            .iter()
+            .filter(|entry| entry.event == LedgerEvent::AutoTopUp)
+            .map(|entry| entry.debit)
+            .sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{OpsConfig, TopUpConfig};
+    use crate::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_test_utils::MockSettlement;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn create_ops(
+        top_up: TopUpConfig,
+        settlement: Arc<MockSettlement>,
+    ) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (_, public_key) = generate_identity();
+        let ops = DefaultNodeOperations::with_config_and_settlement(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default().with_top_up(top_up),
+            settlement,
+        );
+        (ops, temp_dir)
+    }
+
+    fn enabled() -> TopUpConfig {
+        TopUpConfig::default()
+            .with_enabled(true)
+            .with_min_balance(100)
+            .with_amount(300)
+            .with_daily_cap(500)
+    }
+
+    #[tokio::test]
+    async fn test_top_up_disabled_or_not_needed() {
+        let settlement = Arc::new(MockSettlement::new().with_balance(0));
+        let (ops, _temp) = create_ops(TopUpConfig::default(), settlement.clone());
+        assert_eq!(ops.check_top_up().await.unwrap(), None);
+
+        let settlement = Arc::new(MockSettlement::new().with_balance(100));
+        let (ops, _temp) = create_ops(enabled(), settlement.clone());
+        assert_eq!(
+            ops.check_top_up().await.unwrap(),
+            Some(TopUpOutcome::NotNeeded { balance: 100 })
+        );
+        assert!(settlement.deposits().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_top_up_respects_daily_cap() {
+        let settlement = Arc::new(MockSettlement::new().with_balance(0));
+        let (mut ops, _temp) = create_ops(enabled(), settlement.clone());
+        let mut events = ops.subscribe_events();
+
+        let outcome = ops.check_top_up().await.unwrap().unwrap();
+        assert!(matches!(
+            outcome,
+            TopUpOutcome::Deposited {
+                amount: 300,
+                balance: 0,
+                ..
+            }
+        ));
+        assert_eq!(
+            events.try_recv().unwrap(),
+            OpsEvent::SettlementToppedUp {
+                amount: 300,
+                balance: 300
+            }
+        );
+
+        // Above the threshold: nothing to do
+        assert!(matches!(
+            ops.check_top_up().await.unwrap(),
+            Some(TopUpOutcome::NotNeeded { balance: 300 })
+        ));
+
+        // Spent down again: the second top-up is limited by the cap
+        ops.config.top_up.min_balance = 1_000;
+        let outcome = ops.check_top_up().await.unwrap().unwrap();
+        assert!(matches!(
+            outcome,
+            TopUpOutcome::Deposited { amount: 200, .. }
+        ));
+        assert_eq!(settlement.deposits(), vec![300, 200]);
+        assert_eq!(ops.top_ups_today().unwrap(), 500);
+
+        let outcome = ops.check_top_up().await.unwrap().unwrap();
+        assert_eq!(
+            outcome,
+            TopUpOutcome::CapReached {
+                balance: 500,
+                deposited_today: 500
+            }
+        );
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            OpsEvent::SettlementToppedUp { amount: 200, .. }
+        ));
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            OpsEvent::TopUpCapReached { .. }
+        ));
+        assert_eq!(settlement.deposits().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_top_up_cap_survives_restart() {
+        let settlement = Arc::new(MockSettlement::new().with_balance(0));
+        let temp_dir = TempDir::new().unwrap();
+        let open = |settlement: Arc<MockSettlement>| {
+            let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+            let (_, public_key) = generate_identity();
+            DefaultNodeOperations::with_config_and_settlement(
+                state,
+                peer_id_from_public_key(&public_key),
+                OpsConfig::default().with_top_up(enabled().with_min_balance(1_000)),
+                settlement,
+            )
+        };
+
+        let ops = open(settlement.clone());
+        ops.check_top_up().await.unwrap();
+        drop(ops);
+
+        let ops = open(settlement.clone());
+        assert_eq!(ops.top_ups_today().unwrap(), 300);
+        let outcome = ops.check_top_up().await.unwrap().unwrap();
+        assert!(matches!(
+            outcome,
+            TopUpOutcome::Deposited { amount: 200, .. }
+        ));
+    }
+}
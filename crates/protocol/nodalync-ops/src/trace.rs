@@ -0,0 +1,144 @@
+//! Request IDs for correlating traces across nodes.
+//!
+//! Every query runs in a tracing span carrying a [`RequestId`], along with
+//! the content hash, peer and amount where they apply. The ID travels in
+//! the QUERY_REQUEST, and the provider's handler span records it, so the
+//! requester's and the provider's traces of one query can be joined up
+//! (for example in Jaeger, when the node exports traces over OTLP).
+//!
+//! The ID of the operation being run is kept in a task-local, so requests
+//! sent anywhere beneath [`with_request_id`] carry it without it being
+//! passed down explicitly.
+
+use std::future::Future;
+
+use nodalync_wire::RequestId;
+
+tokio::task_local! {
+    static REQUEST_ID: RequestId;
+}
+
+/// A fresh random request ID.
+pub fn new_request_id() -> RequestId {
+    RequestId(rand::random())
+}
+
+/// The request ID of the operation running on this task, if any.
+pub fn current_request_id() -> Option<RequestId> {
+    REQUEST_ID.try_with(|id| *id).ok()
+}
+
+/// The current request ID, or a fresh one outside of any operation.
+pub(crate) fn request_id_or_new() -> RequestId {
+    current_request_id().unwrap_or_else(new_request_id)
+}
+
+/// Run `future` with `id` as the current request ID.
+pub async fn with_request_id<F: Future>(id: RequestId, future: F) -> F::Output {
+    REQUEST_ID.scope(id, future).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OpsConfig;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_net::Network;
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_test_utils::MockNetwork;
+    use nodalync_types::{ContentType, L1Summary, Metadata, Visibility};
+    use nodalync_wire::{AnnouncePayload, QueryRequestPayload};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops = DefaultNodeOperations::with_config(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default(),
+        );
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_request_id_is_scoped_to_the_task() {
+        assert_eq!(current_request_id(), None);
+
+        let id = new_request_id();
+        let seen = with_request_id(id, async {
+            tokio::task::yield_now().await;
+            (current_request_id(), request_id_or_new())
+        })
+        .await;
+        assert_eq!(seen, (Some(id), id));
+
+        assert_eq!(current_request_id(), None);
+        assert_ne!(request_id_or_new(), request_id_or_new());
+    }
+
+    #[tokio::test]
+    async fn test_query_sends_current_request_id() {
+        let (provider, _provider_temp) = create_test_ops();
+        let content = b"Field notes from the survey";
+        let hash = provider
+            .create_content(content, Metadata::new("Notes", content.len() as u64))
+            .unwrap();
+        provider
+            .publish_content(&hash, Visibility::Shared, 0)
+            .await
+            .unwrap();
+        let (mut client, _client_temp) = create_test_ops();
+        let request = QueryRequestPayload {
+            hash,
+            query: None,
+            payment: None,
+            version_spec: None,
+            payment_nonce: 0,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        };
+        let response = provider
+            .handle_query_request(&client.peer_id(), &request)
+            .await
+            .unwrap();
+
+        let provider_peer = nodalync_net::PeerId::random();
+        let announcement = AnnouncePayload {
+            hash,
+            content_type: ContentType::L0,
+            title: "Notes".to_string(),
+            l1_summary: L1Summary::empty(hash),
+            price: 0,
+            addresses: vec![],
+            publisher_peer_id: Some(provider_peer.to_string()),
+            publisher_key: None,
+            signature: None,
+            delegation: None,
+        };
+        let network = Arc::new(
+            MockNetwork::new()
+                .with_dht_entry(hash, announcement)
+                .with_query_response(hash, response),
+        );
+        client.set_network(Arc::clone(&network) as Arc<dyn Network>);
+
+        let id = new_request_id();
+        with_request_id(id, client.query_content(&hash, 0, None))
+            .await
+            .unwrap();
+        let requests = network.query_requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].1.request_id, Some(id));
+    }
+}
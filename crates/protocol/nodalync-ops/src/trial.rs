@@ -0,0 +1,366 @@
+//! Free trial reads of paid content.
+//!
+//! An owner can set a [`TrialPolicy`] in `access.trial`, letting each
+//! requester read the content free a few times, or only its first bytes,
+//! before having to pay. Casual browsers get to try the content without
+//! being allowlisted:
+//!
+//! - A query without a payment for paid content is served as a trial read
+//!   if the requester has reads left, counted per requester in the store;
+//!   the usual access checks still apply
+//! - Trial reads earn nothing and don't count as queries in the economics;
+//!   the response says how many free reads the requester has left
+//! - A trial covers the content itself, not a section or metered unit
+//!
+//! Trial reads are not cached. Only a whole read can be checked against
+//! the content hash; a read cut to the free bytes is unverified.
+
+use nodalync_crypto::{content_hash, Hash, PeerId, Timestamp};
+use nodalync_store::{AccessKind, ContentStore, ManifestStore, PopularityKind, TrialStore};
+use nodalync_types::{Manifest, TrialPolicy};
+use nodalync_valid::{validate_trial, Validator};
+use nodalync_wire::{PaymentReceipt, QueryRequestPayload, QueryResponsePayload};
+use tracing::{debug, warn};
+
+use crate::error::{OpsError, OpsResult};
+use crate::events::OpsEvent;
+use crate::extraction::L1Extractor;
+use crate::helpers::verify_content_hash;
+use crate::node_ops::{lock, NodeOperations};
+use crate::trace::request_id_or_new;
+
+/// Response from a trial read.
+#[derive(Debug, Clone)]
+pub struct TrialResponse {
+    /// The content, or just its free leading bytes.
+    pub content: Vec<u8>,
+    /// The content manifest.
+    pub manifest: Manifest,
+    /// Whether the whole content was read.
+    pub complete: bool,
+    /// Free reads left after this one (None for own content).
+    pub reads_left: Option<u32>,
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Offer free trial reads of owned content; `None` stops offering them.
+    ///
+    /// Reads already served stay counted, so a requester who used up an
+    /// earlier trial doesn't get a new one.
+    pub fn set_content_trial(&self, hash: &Hash, trial: Option<TrialPolicy>) -> OpsResult<()> {
+        let manifest = self
+            .state
+            .manifests
+            .load(hash)?
+            .ok_or(OpsError::ManifestNotFound(*hash))?;
+
+        let mut access = manifest.access;
+        access.trial = trial;
+        self.set_content_access(hash, access)
+    }
+
+    /// Number of peers that have had free trial reads of content.
+    pub fn trial_readers(&self, hash: &Hash) -> OpsResult<u32> {
+        Ok(self.state.trials.readers(hash)?)
+    }
+
+    /// Read paid content free, under its trial policy.
+    ///
+    /// Fails with `TrialExhausted` if the content offers no trial or this
+    /// node has used up its free reads. Own content is read whole.
+    pub async fn query_trial(&self, hash: &Hash) -> OpsResult<TrialResponse> {
+        // 1. Own content needs no trial
+        if let Some(manifest) = self.state.manifests.load(hash)? {
+            if manifest.owner == self.peer_id() {
+                let content = self
+                    .state
+                    .content
+                    .load(hash)?
+                    .ok_or(OpsError::NotFound(*hash))?;
+                return Ok(TrialResponse {
+                    content,
+                    manifest,
+                    complete: true,
+                    reads_left: None,
+                });
+            }
+        }
+
+        // 2. The provider's manifest says whether a trial is offered
+        let (network, provider, manifest) = self.provider_manifest(hash).await?;
+        validate_trial(&manifest, 0)?;
+        self.ensure_trusted(&manifest)?;
+
+        // 3. Ask for the content without a payment
+        let request = QueryRequestPayload {
+            hash: *hash,
+            query: None,
+            payment: None,
+            version_spec: None,
+            payment_nonce: 0,
+            capability: None,
+            recipient_key: self.recipient_key(),
+            challenge_response: None,
+            request_id: Some(request_id_or_new()),
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        };
+        let mut response = self
+            .send_query_answering_challenge(&network, provider, request)
+            .await?;
+        self.open_response(&mut response)?;
+
+        // 4. Whole content must match its hash, and a cut read must be a
+        // prefix no longer than the free bytes
+        let content = response.content.into_vec();
+        let complete = content.len() as u64 == manifest.metadata.content_size;
+        let free_bytes = manifest.access.trial.and_then(|trial| trial.bytes);
+        if complete {
+            if !verify_content_hash(&content, hash) {
+                return Err(OpsError::ContentHashMismatch);
+            }
+        } else if free_bytes.is_none_or(|bytes| content.len() as u64 > bytes) {
+            warn!(hash = %hash, provider = %provider, "Trial read is not the free bytes");
+            return Err(OpsError::ContentHashMismatch);
+        }
+        self.screen_remote_content(&manifest)?;
+
+        Ok(TrialResponse {
+            content,
+            manifest,
+            complete,
+            reads_left: Some(response.trial_reads_left.unwrap_or(0)),
+        })
+    }
+
+    /// Serve a query without a payment for paid content as a trial read.
+    ///
+    /// Access has already been checked. Refused with `PaymentInsufficient`
+    /// if the content offers no trial, or for a section or metered unit.
+    pub(crate) fn handle_trial_query(
+        &self,
+        requester: &PeerId,
+        request: &QueryRequestPayload,
+        manifest: Manifest,
+        timestamp: Timestamp,
+    ) -> OpsResult<QueryResponsePayload> {
+        let trial = match manifest.access.trial {
+            Some(trial) if request.section.is_none() && request.unit.is_none() => trial,
+            _ => return Err(OpsError::PaymentInsufficient),
+        };
+
+        // Count the read under the requester's channel lock, so concurrent
+        // requests can't both take its last free read
+        let peer_lock = self.channel_locks.for_peer(requester);
+        let peer_guard = lock(&peer_lock);
+        let used = self.state.trials.reads(&request.hash, requester)?;
+        validate_trial(&manifest, used)?;
+
+        let mut content = self.load_served_content(request, &manifest)?;
+        let whole = match trial.bytes {
+            Some(bytes) if bytes < content.len() as u64 => {
+                content = content[..bytes as usize].into();
+                false
+            }
+            _ => true,
+        };
+        let used = self
+            .state
+            .trials
+            .record_read(&request.hash, requester, timestamp)?;
+        drop(peer_guard);
+
+        self.record_access(requester, &request.hash, AccessKind::Query, 0);
+        self.record_popularity(&request.hash, PopularityKind::Query);
+
+        let payment_id =
+            content_hash(&[request.hash.0.as_slice(), &timestamp.to_be_bytes()].concat());
+        let receipt = PaymentReceipt {
+            payment_id,
+            amount: 0,
+            timestamp,
+            channel_nonce: 0,
+            distributor_signature: self.sign_receipt(&payment_id, 0, timestamp, 0),
+            app_fee: 0,
+            app_fee_recipient: None,
+            referral_fee: 0,
+            referrer: None,
+        };
+
+        let (content, content_key) =
+            self.seal_content(&manifest, content, request.recipient_key.as_ref())?;
+        // Delivery commitments cover whole content only
+        let delivery = if whole && content_key.is_none() {
+            self.commit_delivery(&request.hash, &content, &payment_id, requester)
+        } else {
+            None
+        };
+
+        debug!(hash = %request.hash, requester = %requester, reads = used, "Served trial read");
+        self.emit(OpsEvent::ContentQueried {
+            content_hash: request.hash,
+            requester: *requester,
+            amount: 0,
+        });
+
+        Ok(QueryResponsePayload {
+            hash: request.hash,
+            content,
+            manifest,
+            payment_receipt: receipt,
+            bundle: Vec::new(),
+            content_key,
+            delivery,
+            section: None,
+            unit: None,
+            trial_reads_left: Some(trial.reads_left(used)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::NodeStateConfig;
+    use nodalync_types::{Metadata, Visibility};
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let config = NodeStateConfig::new(temp_dir.path());
+        let state = nodalync_store::NodeState::open(config).unwrap();
+
+        let (_, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+
+        let ops = DefaultNodeOperations::with_defaults(state, peer_id);
+        (ops, temp_dir)
+    }
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    fn trial_request(hash: Hash) -> QueryRequestPayload {
+        QueryRequestPayload {
+            hash,
+            query: None,
+            payment: None,
+            version_spec: None,
+            payment_nonce: 0,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_set_content_trial() {
+        let (ops, _temp) = create_test_ops();
+        let content = b"A paid report";
+        let hash = ops
+            .create_content(content, Metadata::new("Report", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 1_000)
+            .await
+            .unwrap();
+
+        ops.set_content_trial(&hash, Some(TrialPolicy::new(2).with_bytes(4)))
+            .unwrap();
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        assert_eq!(
+            manifest.access.trial,
+            Some(TrialPolicy::new(2).with_bytes(4))
+        );
+
+        assert!(ops
+            .set_content_trial(&hash, Some(TrialPolicy::new(0)))
+            .is_err());
+
+        // Own content is read whole
+        let response = ops.query_trial(&hash).await.unwrap();
+        assert!(response.complete);
+        assert_eq!(response.content, content);
+
+        ops.set_content_trial(&hash, None).unwrap();
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        assert!(manifest.access.trial.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_trial_reads() {
+        let (ops, _temp) = create_test_ops();
+        let content = b"The first bytes are free";
+        let hash = ops
+            .create_content(content, Metadata::new("Report", content.len() as u64))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 1_000)
+            .await
+            .unwrap();
+        let requester = test_peer_id();
+
+        // Paid content without a trial needs a payment
+        let result = ops
+            .handle_query_request(&requester, &trial_request(hash))
+            .await;
+        assert!(matches!(result, Err(OpsError::PaymentInsufficient)));
+
+        ops.set_content_trial(&hash, Some(TrialPolicy::new(2).with_bytes(9)))
+            .unwrap();
+        for left in [1, 0] {
+            let response = ops
+                .handle_query_request(&requester, &trial_request(hash))
+                .await
+                .unwrap();
+            assert_eq!(response.content, b"The first".to_vec());
+            assert_eq!(response.payment_receipt.amount, 0);
+            assert_eq!(response.trial_reads_left, Some(left));
+            assert!(response.delivery.is_none());
+        }
+
+        // Used up for this requester, but not for others
+        let result = ops
+            .handle_query_request(&requester, &trial_request(hash))
+            .await;
+        assert!(matches!(
+            result,
+            Err(OpsError::Validation(
+                nodalync_valid::ValidationError::TrialExhausted { allowed: 2 }
+            ))
+        ));
+        let response = ops
+            .handle_query_request(&test_peer_id(), &trial_request(hash))
+            .await
+            .unwrap();
+        assert_eq!(response.trial_reads_left, Some(1));
+        assert_eq!(ops.trial_readers(&hash).unwrap(), 2);
+
+        // Trial reads are not sales
+        let manifest = ops.get_content_manifest(&hash).unwrap().unwrap();
+        assert_eq!(manifest.economics.total_queries, 0);
+
+        // A trial without a byte limit serves the whole content
+        ops.set_content_trial(&hash, Some(TrialPolicy::new(3)))
+            .unwrap();
+        let response = ops
+            .handle_query_request(&requester, &trial_request(hash))
+            .await
+            .unwrap();
+        assert_eq!(response.content, content.to_vec());
+        assert_eq!(response.trial_reads_left, Some(0));
+    }
+}
@@ -0,0 +1,516 @@
+//! Trust policy for content received from other peers.
+//!
+//! Content fetched from the network is scored against the configured
+//! [`TrustPolicy`] before it is cached or returned. These checks are
+//! applied, each only when the policy configures it:
+//!
+//! - **Reputation** - the publisher has at least `min_reputation`
+//!   (unknown publishers count as 0).
+//! - **Attestation** - the manifest claims an on-chain attestation. The
+//!   claim itself is checked against the chain before paying for derived
+//!   content (see [`crate::attestation`]).
+//! - **Depth** - the provenance depth is at most `max_depth`.
+//! - **License** - the `license` field of the structured metadata is
+//!   allowed, or absent when no license is required.
+//! - **Price** - the price is at most `max_price`.
+//!
+//! The score is the weighted share of passing checks. Rejected content is
+//! not cached and its query fails with [`OpsError::TrustRejected`]; when
+//! the manifest is known before paying, rejection happens before payment.
+//! Flagged content is kept, and reported with [`OpsEvent::ContentFlagged`].
+//! Content owned by a revoked key is rejected before it is scored (see
+//! [`crate::revocation`]). Our own content is never screened.
+
+use std::fmt;
+use std::str::FromStr;
+
+use nodalync_store::PeerStore;
+use nodalync_types::Manifest;
+use nodalync_valid::Validator;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::TrustPolicy;
+use crate::error::{OpsError, OpsResult};
+use crate::events::OpsEvent;
+use crate::extraction::L1Extractor;
+use crate::node_ops::NodeOperations;
+
+/// A check of the trust policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrustCheck {
+    /// Publisher reputation
+    Reputation,
+    /// Attestation claim
+    Attestation,
+    /// Provenance depth
+    Depth,
+    /// Declared license
+    License,
+    /// Price
+    Price,
+}
+
+impl TrustCheck {
+    /// All checks, in evaluation order.
+    pub const ALL: [TrustCheck; 5] = [
+        TrustCheck::Reputation,
+        TrustCheck::Attestation,
+        TrustCheck::Depth,
+        TrustCheck::License,
+        TrustCheck::Price,
+    ];
+
+    /// Get the check's name, as used in policy files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrustCheck::Reputation => "reputation",
+            TrustCheck::Attestation => "attestation",
+            TrustCheck::Depth => "depth",
+            TrustCheck::License => "license",
+            TrustCheck::Price => "price",
+        }
+    }
+}
+
+impl fmt::Display for TrustCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TrustCheck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|check| check.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("unknown trust check '{}'", s))
+    }
+}
+
+/// What to do with content after scoring it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrustOutcome {
+    /// Use the content
+    Accept,
+    /// Use the content, but report it
+    Flag,
+    /// Don't cache or use the content
+    Reject,
+}
+
+impl fmt::Display for TrustOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TrustOutcome::Accept => "accept",
+            TrustOutcome::Flag => "flag",
+            TrustOutcome::Reject => "reject",
+        })
+    }
+}
+
+/// Result of one configured check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustFinding {
+    /// The check
+    pub check: TrustCheck,
+    /// Whether the content passed it
+    pub passed: bool,
+    /// What was found
+    pub detail: String,
+}
+
+/// Result of scoring content against a trust policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustEvaluation {
+    /// Weighted share of passing checks (0-100)
+    pub score: u8,
+    /// What to do with the content
+    pub outcome: TrustOutcome,
+    /// Results of the configured checks
+    pub findings: Vec<TrustFinding>,
+}
+
+impl TrustEvaluation {
+    /// Get the failed checks, as "check: detail".
+    pub fn reasons(&self) -> Vec<String> {
+        self.findings
+            .iter()
+            .filter(|f| !f.passed)
+            .map(|f| format!("{}: {}", f.check, f.detail))
+            .collect()
+    }
+}
+
+impl TrustPolicy {
+    /// Score a manifest.
+    ///
+    /// `reputation` is the publisher's reputation, if the peer is known.
+    pub fn evaluate(&self, manifest: &Manifest, reputation: Option<i64>) -> TrustEvaluation {
+        let mut findings = Vec::new();
+
+        if let Some(min) = self.min_reputation {
+            let reputation = reputation.unwrap_or(0);
+            findings.push(TrustFinding {
+                check: TrustCheck::Reputation,
+                passed: reputation >= min,
+                detail: format!("publisher reputation {} (minimum {})", reputation, min),
+            });
+        }
+
+        if self.require_attestation {
+            let attested = manifest.provenance.attested;
+            findings.push(TrustFinding {
+                check: TrustCheck::Attestation,
+                passed: attested,
+                detail: if attested {
+                    "attestation claimed".to_string()
+                } else {
+                    "no attestation claimed".to_string()
+                },
+            });
+        }
+
+        if let Some(max) = self.max_depth {
+            let depth = manifest.provenance.depth;
+            findings.push(TrustFinding {
+                check: TrustCheck::Depth,
+                passed: depth <= max,
+                detail: format!("provenance depth {} (maximum {})", depth, max),
+            });
+        }
+
+        if self.require_license || !self.allowed_licenses.is_empty() {
+            findings.push(self.check_license(declared_license(manifest).as_deref()));
+        }
+
+        if let Some(max) = self.max_price {
+            let price = manifest.economics.price;
+            findings.push(TrustFinding {
+                check: TrustCheck::Price,
+                passed: price <= max,
+                detail: format!("price {} (maximum {})", price, max),
+            });
+        }
+
+        let total: u64 = findings
+            .iter()
+            .map(|f| self.weights.of(f.check) as u64)
+            .sum();
+        let passed: u64 = findings
+            .iter()
+            .filter(|f| f.passed)
+            .map(|f| self.weights.of(f.check) as u64)
+            .sum();
+        let score = (passed * 100).checked_div(total).unwrap_or(100) as u8;
+
+        let rejected_check = findings
+            .iter()
+            .any(|f| !f.passed && self.reject_on.contains(&f.check));
+        let outcome = if rejected_check || score < self.reject_below {
+            TrustOutcome::Reject
+        } else if score < self.flag_below {
+            TrustOutcome::Flag
+        } else {
+            TrustOutcome::Accept
+        };
+
+        TrustEvaluation {
+            score,
+            outcome,
+            findings,
+        }
+    }
+
+    fn check_license(&self, license: Option<&str>) -> TrustFinding {
+        let (passed, detail) = match license {
+            None if self.require_license => (false, "no license declared".to_string()),
+            None => (true, "no license declared".to_string()),
+            Some(license) => {
+                let allowed = self.allowed_licenses.is_empty()
+                    || self
+                        .allowed_licenses
+                        .iter()
+                        .any(|a| a.trim().eq_ignore_ascii_case(license.trim()));
+                let detail = if allowed {
+                    format!("license {}", license)
+                } else {
+                    format!("license {} is not allowed", license)
+                };
+                (allowed, detail)
+            }
+        };
+        TrustFinding {
+            check: TrustCheck::License,
+            passed,
+            detail,
+        }
+    }
+}
+
+/// Get the `license` field of a manifest's structured metadata.
+fn declared_license(manifest: &Manifest) -> Option<String> {
+    let fields: serde_json::Value =
+        serde_json::from_str(manifest.metadata.fields.as_deref()?).ok()?;
+    fields
+        .get("license")?
+        .as_str()
+        .map(str::to_string)
+        .filter(|l| !l.trim().is_empty())
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Score a manifest against the configured trust policy.
+    pub fn evaluate_trust(&self, manifest: &Manifest) -> TrustEvaluation {
+        let reputation = self
+            .state
+            .peers
+            .get(&manifest.owner)
+            .ok()
+            .flatten()
+            .map(|info| info.reputation);
+        self.config.trust.evaluate(manifest, reputation)
+    }
+
+    /// Refuse to pay for content the trust policy rejects, or content
+    /// owned by a revoked key.
+    pub(crate) fn ensure_trusted(&self, manifest: &Manifest) -> OpsResult<()> {
+        if manifest.owner == self.peer_id() {
+            return Ok(());
+        }
+        self.ensure_owner_not_revoked(manifest)?;
+        let evaluation = self.evaluate_trust(manifest);
+        if evaluation.outcome == TrustOutcome::Reject {
+            return Err(trust_rejection(manifest, &evaluation));
+        }
+        Ok(())
+    }
+
+    /// Screen content received from a peer before caching it.
+    ///
+    /// Rejected content, and content owned by a revoked key, is an error;
+    /// flagged content is reported on the event bus and accepted.
+    pub(crate) fn screen_remote_content(&self, manifest: &Manifest) -> OpsResult<()> {
+        if manifest.owner == self.peer_id() {
+            return Ok(());
+        }
+        self.ensure_owner_not_revoked(manifest)?;
+        let evaluation = self.evaluate_trust(manifest);
+        match evaluation.outcome {
+            TrustOutcome::Accept => Ok(()),
+            TrustOutcome::Flag => {
+                let reasons = evaluation.reasons();
+                warn!(
+                    hash = %manifest.hash,
+                    score = evaluation.score,
+                    "Content flagged by trust policy: {}",
+                    reasons.join("; ")
+                );
+                self.emit(OpsEvent::ContentFlagged {
+                    hash: manifest.hash,
+                    score: evaluation.score,
+                    reasons,
+                });
+                Ok(())
+            }
+            TrustOutcome::Reject => Err(trust_rejection(manifest, &evaluation)),
+        }
+    }
+}
+
+fn trust_rejection(manifest: &Manifest, evaluation: &TrustEvaluation) -> OpsError {
+    let mut reasons = evaluation.reasons();
+    if reasons.is_empty() {
+        reasons.push(format!("score {}", evaluation.score));
+    }
+    let reason = reasons.join("; ");
+    warn!(hash = %manifest.hash, "Content rejected by trust policy: {}", reason);
+    OpsError::TrustRejected {
+        hash: manifest.hash,
+        reason,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TrustWeights;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key, PeerId};
+    use nodalync_store::{NodeState, NodeStateConfig, PeerInfo};
+    use nodalync_types::Metadata;
+    use tempfile::TempDir;
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    fn remote_manifest(owner: PeerId, license: Option<&str>, price: u64) -> Manifest {
+        let hash = content_hash(b"remote content");
+        let mut metadata = Metadata::new("Remote", 14);
+        if let Some(license) = license {
+            metadata = metadata.with_fields(
+                "nodalync:schema/dataset/v1",
+                format!(r#"{{"format":"csv","license":"{}"}}"#, license),
+            );
+        }
+        let mut manifest = Manifest::new_l0(hash, owner, metadata, 1_000);
+        manifest.economics.price = price;
+        manifest
+    }
+
+    fn policy() -> TrustPolicy {
+        TrustPolicy::default()
+            .with_min_reputation(10)
+            .with_require_attestation(true)
+            .with_max_depth(3)
+            .with_allowed_licenses(vec!["CC-BY-4.0".to_string(), "MIT".to_string()])
+            .with_max_price(500)
+    }
+
+    #[test]
+    fn test_default_policy_accepts_everything() {
+        let manifest = remote_manifest(test_peer_id(), None, 1_000_000);
+        let evaluation = TrustPolicy::default().evaluate(&manifest, None);
+        assert_eq!(evaluation.outcome, TrustOutcome::Accept);
+        assert_eq!(evaluation.score, 100);
+        assert!(evaluation.findings.is_empty());
+    }
+
+    #[test]
+    fn test_scoring_and_outcomes() {
+        let mut manifest = remote_manifest(test_peer_id(), Some("cc-by-4.0"), 100);
+        manifest.provenance.attested = true;
+
+        let evaluation = policy().evaluate(&manifest, Some(20));
+        assert_eq!(evaluation.outcome, TrustOutcome::Accept);
+        assert_eq!(evaluation.findings.len(), 5);
+
+        // Unknown publishers count as 0, and any failure flags by default
+        let evaluation = policy().evaluate(&manifest, None);
+        assert_eq!(evaluation.outcome, TrustOutcome::Flag);
+        assert_eq!(evaluation.score, 80);
+        assert_eq!(evaluation.reasons().len(), 1);
+        assert!(evaluation.reasons()[0].starts_with("reputation"));
+
+        // Weights shift the score, thresholds turn it into a rejection
+        let weighted = policy()
+            .with_weights(TrustWeights {
+                reputation: 6,
+                ..TrustWeights::default()
+            })
+            .with_thresholds(100, 50);
+        let evaluation = weighted.evaluate(&manifest, None);
+        assert_eq!(evaluation.score, 40);
+        assert_eq!(evaluation.outcome, TrustOutcome::Reject);
+
+        // Checks in reject_on reject regardless of the score
+        let strict = policy().with_reject_on(vec![TrustCheck::License, TrustCheck::Price]);
+        let unlicensed = remote_manifest(manifest.owner, Some("GPL-3.0"), 100);
+        let mut unlicensed = unlicensed;
+        unlicensed.provenance.attested = true;
+        let evaluation = strict.evaluate(&unlicensed, Some(20));
+        assert_eq!(evaluation.score, 80);
+        assert_eq!(evaluation.outcome, TrustOutcome::Reject);
+
+        let expensive = remote_manifest(manifest.owner, None, 501);
+        let evaluation = strict.evaluate(&expensive, Some(20));
+        assert_eq!(evaluation.outcome, TrustOutcome::Reject);
+    }
+
+    #[test]
+    fn test_license_check() {
+        let owner = test_peer_id();
+        let unlicensed = remote_manifest(owner, None, 0);
+        let allowed_only = TrustPolicy::default().with_allowed_licenses(vec!["MIT".to_string()]);
+        assert_eq!(
+            allowed_only.evaluate(&unlicensed, None).outcome,
+            TrustOutcome::Accept
+        );
+
+        let required = TrustPolicy::default().with_require_license(true);
+        assert_eq!(
+            required.evaluate(&unlicensed, None).outcome,
+            TrustOutcome::Flag
+        );
+        assert_eq!(
+            required
+                .evaluate(&remote_manifest(owner, Some("Proprietary"), 0), None)
+                .outcome,
+            TrustOutcome::Accept
+        );
+    }
+
+    #[test]
+    fn test_check_names() {
+        for check in TrustCheck::ALL {
+            assert_eq!(check.as_str().parse::<TrustCheck>().unwrap(), check);
+        }
+        assert_eq!(
+            " License ".parse::<TrustCheck>().unwrap(),
+            TrustCheck::License
+        );
+        assert!("size".parse::<TrustCheck>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_screen_remote_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let mut ops = DefaultNodeOperations::with_defaults(state, test_peer_id());
+        ops.config.trust = TrustPolicy::default()
+            .with_min_reputation(5)
+            .with_max_price(100)
+            .with_reject_on(vec![TrustCheck::Price]);
+        let mut events = ops.subscribe_events();
+
+        // Reputation comes from the peer store
+        let (_, publisher_key) = generate_identity();
+        let publisher = peer_id_from_public_key(&publisher_key);
+        let mut info = PeerInfo::new(publisher, publisher_key, vec![], 1_000);
+        info.reputation = 5;
+        ops.state.peers.upsert(&info).unwrap();
+        let manifest = remote_manifest(publisher, None, 50);
+        assert_eq!(ops.evaluate_trust(&manifest).outcome, TrustOutcome::Accept);
+        ops.screen_remote_content(&manifest).unwrap();
+
+        // Flagged content is accepted and reported
+        let stranger = remote_manifest(test_peer_id(), None, 50);
+        ops.screen_remote_content(&stranger).unwrap();
+        match events.try_recv().unwrap() {
+            OpsEvent::ContentFlagged {
+                hash,
+                score,
+                reasons,
+            } => {
+                assert_eq!(hash, stranger.hash);
+                assert_eq!(score, 50);
+                assert_eq!(reasons.len(), 1);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        // Rejected content is refused before and after payment
+        let expensive = remote_manifest(publisher, None, 150);
+        assert!(matches!(
+            ops.ensure_trusted(&expensive),
+            Err(OpsError::TrustRejected { .. })
+        ));
+        assert!(matches!(
+            ops.screen_remote_content(&expensive),
+            Err(OpsError::TrustRejected { .. })
+        ));
+
+        // Our own content is never screened
+        let own = remote_manifest(ops.peer_id(), None, 150);
+        ops.screen_remote_content(&own).unwrap();
+    }
+}
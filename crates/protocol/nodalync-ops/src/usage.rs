@@ -0,0 +1,306 @@
+//! Usage reports (read receipts).
+//!
+//! After using queried content, a consumer that opted in
+//! (`UsageReportConfig::send`) can tell the publisher how much of it was
+//! read, how large the model context was, and how useful it turned out to
+//! be. A publisher that opted in (`UsageReportConfig::accept`) records the
+//! report without the sender and aggregates it into `ContentStats::usage`.
+//! Reports are rate-limited per sending peer.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use nodalync_crypto::{Hash, PeerId};
+use nodalync_store::CacheStore;
+use nodalync_valid::Validator;
+use nodalync_wire::{UsageReportPayload, MAX_USAGE_RATING};
+use tracing::info;
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::NodeOperations;
+
+/// Window over which `UsageReportConfig::max_per_peer_per_hour` applies.
+const USAGE_REPORT_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Per-peer rate limiting of incoming usage reports.
+#[derive(Debug, Default)]
+pub(crate) struct UsageReportLimiter {
+    /// Times of recently accepted reports, per sending peer.
+    recent: HashMap<PeerId, VecDeque<Instant>>,
+}
+
+impl UsageReportLimiter {
+    /// Record a report from `sender`, returning false if it is over the
+    /// rate limit.
+    pub(crate) fn check(&mut self, sender: &PeerId, max_per_hour: u32) -> bool {
+        let now = Instant::now();
+        self.recent.retain(|_, times| {
+            times
+                .back()
+                .is_some_and(|t| now.duration_since(*t) < USAGE_REPORT_WINDOW)
+        });
+
+        let times = self.recent.entry(*sender).or_default();
+        while times
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= USAGE_REPORT_WINDOW)
+        {
+            times.pop_front();
+        }
+        if times.len() >= max_per_hour as usize {
+            return false;
+        }
+        times.push_back(now);
+        true
+    }
+}
+
+/// Check that an outcome rating is within 1 to [`MAX_USAGE_RATING`].
+pub(crate) fn validate_rating(rating: Option<u8>) -> OpsResult<()> {
+    match rating {
+        Some(r) if r == 0 || r > MAX_USAGE_RATING => Err(OpsError::invalid_operation(format!(
+            "rating must be between 1 and {}",
+            MAX_USAGE_RATING
+        ))),
+        _ => Ok(()),
+    }
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Report how queried content was used back to its publisher.
+    ///
+    /// Only content we queried (and so have cached) can be reported on.
+    /// Requires `UsageReportConfig::send`; the publisher may still refuse
+    /// the report if it does not accept reports or we are over its rate
+    /// limit.
+    pub async fn report_usage(
+        &self,
+        hash: &Hash,
+        bytes_read: u64,
+        context_tokens: Option<u32>,
+        rating: Option<u8>,
+    ) -> OpsResult<()> {
+        if !self.config.usage_reports.send {
+            return Err(OpsError::invalid_operation("usage reports are disabled"));
+        }
+        validate_rating(rating)?;
+
+        let cached = self
+            .state
+            .cache
+            .get(hash)?
+            .ok_or(OpsError::NotFound(*hash))?;
+
+        let network = self
+            .network()
+            .cloned()
+            .ok_or_else(|| OpsError::invalid_operation("network required to report usage"))?;
+        let libp2p_peer = network
+            .libp2p_peer_id(&cached.source_peer)
+            .or_else(|| {
+                self.state
+                    .get_announcement(hash)
+                    .and_then(|announce| announce.publisher_peer_id)
+                    .and_then(|peer| peer.parse().ok())
+            })
+            .ok_or(OpsError::PeerIdNotFound)?;
+
+        let ack = network
+            .send_usage_report(
+                libp2p_peer,
+                UsageReportPayload {
+                    hash: *hash,
+                    bytes_read,
+                    context_tokens,
+                    rating,
+                },
+            )
+            .await?;
+        if !ack.accepted {
+            return Err(OpsError::UsageReportRejected(
+                ack.reason.unwrap_or_else(|| "no reason given".to_string()),
+            ));
+        }
+
+        info!(hash = %hash, bytes_read, "Sent usage report");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{OpsConfig, UsageReportConfig};
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_store::{AccessLogStore, CachedContent, NodeState, NodeStateConfig};
+    use nodalync_test_utils::MockNetwork;
+    use nodalync_types::{Metadata, Visibility};
+    use nodalync_wire::PaymentReceipt;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn create_test_ops(usage_reports: UsageReportConfig) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (private_key, public_key) = generate_identity();
+        let mut ops = DefaultNodeOperations::with_config(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default().with_usage_reports(usage_reports),
+        );
+        ops.set_private_key(private_key);
+        (ops, temp_dir)
+    }
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    fn report(hash: Hash, rating: Option<u8>) -> UsageReportPayload {
+        UsageReportPayload {
+            hash,
+            bytes_read: 10,
+            context_tokens: Some(1_000),
+            rating,
+        }
+    }
+
+    async fn publish(ops: &mut DefaultNodeOperations) -> Hash {
+        let content = b"Usage reported content";
+        let meta = Metadata::new("Report me", content.len() as u64);
+        let hash = ops.create_content(content, meta).unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 0)
+            .await
+            .unwrap();
+        hash
+    }
+
+    #[tokio::test]
+    async fn test_report_usage() {
+        let (mut ops, _temp) = create_test_ops(UsageReportConfig::default().with_send(true));
+        let hash = content_hash(b"queried");
+        let publisher = test_peer_id();
+
+        // Only queried (cached) content can be reported on
+        assert!(matches!(
+            ops.report_usage(&hash, 10, None, Some(4)).await,
+            Err(OpsError::NotFound(_))
+        ));
+
+        ops.state
+            .cache
+            .cache(CachedContent::new(
+                hash,
+                b"queried".to_vec(),
+                publisher,
+                1_000,
+                PaymentReceipt {
+                    payment_id: content_hash(b"payment"),
+                    amount: 0,
+                    timestamp: 1_000,
+                    channel_nonce: 1,
+                    distributor_signature: nodalync_crypto::Signature::from_bytes([0u8; 64]),
+                    app_fee: 0,
+                    app_fee_recipient: None,
+                    referral_fee: 0,
+                    referrer: None,
+                },
+            ))
+            .unwrap();
+
+        let libp2p_peer = nodalync_net::PeerId::random();
+        let network = MockNetwork::new().with_peer_mapping(libp2p_peer, publisher);
+        ops.set_network(Arc::new(network.clone()));
+
+        assert!(ops.report_usage(&hash, 10, None, Some(6)).await.is_err());
+        ops.report_usage(&hash, 7, Some(8_000), Some(4))
+            .await
+            .unwrap();
+
+        let sent = network.usage_reports();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].0, libp2p_peer);
+        assert_eq!(sent[0].1.bytes_read, 7);
+        assert_eq!(sent[0].1.rating, Some(4));
+    }
+
+    #[tokio::test]
+    async fn test_report_usage_requires_opt_in() {
+        let (ops, _temp) = create_test_ops(UsageReportConfig::default());
+        assert!(matches!(
+            ops.report_usage(&content_hash(b"queried"), 10, None, None)
+                .await,
+            Err(OpsError::InvalidOperation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_handle_usage_report() {
+        let (mut ops, _temp) = create_test_ops(
+            UsageReportConfig::default()
+                .with_accept(true)
+                .with_max_per_peer_per_hour(2),
+        );
+        let hash = publish(&mut ops).await;
+        let sender = test_peer_id();
+
+        // Unknown content, bad ratings, and overlong reads are refused
+        assert!(
+            !ops.handle_usage_report(&sender, &report(content_hash(b"unknown"), None))
+                .accepted
+        );
+        assert!(
+            !ops.handle_usage_report(&sender, &report(hash, Some(0)))
+                .accepted
+        );
+        let mut overlong = report(hash, None);
+        overlong.bytes_read = 1_000_000;
+        assert!(!ops.handle_usage_report(&sender, &overlong).accepted);
+
+        assert!(
+            ops.handle_usage_report(&sender, &report(hash, Some(5)))
+                .accepted
+        );
+        assert!(
+            ops.handle_usage_report(&sender, &report(hash, Some(3)))
+                .accepted
+        );
+
+        // Over the per-peer limit; other peers are unaffected
+        let ack = ops.handle_usage_report(&sender, &report(hash, Some(1)));
+        assert!(!ack.accepted);
+        assert!(ack.reason.unwrap().contains("rate limit"));
+        assert!(
+            ops.handle_usage_report(&test_peer_id(), &report(hash, None))
+                .accepted
+        );
+
+        let stats = ops.content_stats(&hash).unwrap();
+        assert_eq!(stats.usage.reports, 3);
+        assert_eq!(stats.usage.bytes_read, 30);
+        assert_eq!(stats.usage.average_rating(), Some(4.0));
+        assert_eq!(stats.usage.average_context_tokens(), Some(1_000));
+    }
+
+    #[tokio::test]
+    async fn test_handle_usage_report_requires_opt_in() {
+        let (mut ops, _temp) = create_test_ops(UsageReportConfig::default());
+        let hash = publish(&mut ops).await;
+
+        let ack = ops.handle_usage_report(&test_peer_id(), &report(hash, None));
+        assert!(!ack.accepted);
+        assert!(ops
+            .state
+            .access_log
+            .usage_for_content(&hash)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,351 @@
+//! Version chain verification and repair.
+//!
+//! Each version's manifest links to its predecessor through
+//! `version.previous`. If an intermediate manifest is missing, listing the
+//! versions of a root silently returns part of the history.
+//! [`NodeOperations::verify_version_chain`] reports such gaps, along with
+//! forks (two versions claiming the same predecessor), and
+//! [`NodeOperations::repair_version_chain`] fills gaps with manifests
+//! fetched from the owner in a VERSION_REQUEST.
+
+use std::collections::{HashMap, HashSet};
+
+use nodalync_crypto::Hash;
+use nodalync_store::ManifestStore;
+use nodalync_types::Manifest;
+use nodalync_valid::Validator;
+use nodalync_wire::VersionRequestPayload;
+use tracing::{info, warn};
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::NodeOperations;
+
+/// A version whose predecessor's manifest is missing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionGap {
+    /// The version we hold.
+    pub hash: Hash,
+    /// Its version number.
+    pub number: u32,
+    /// The predecessor we don't hold.
+    pub missing: Hash,
+}
+
+/// Versions that claim the same predecessor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionFork {
+    /// The shared predecessor.
+    pub previous: Hash,
+    /// The competing successors, in version number order.
+    pub branches: Vec<Hash>,
+}
+
+/// Result of checking a version chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionChainReport {
+    /// Version root of the chain.
+    pub root: Hash,
+    /// Manifests held for the chain.
+    pub versions: usize,
+    /// Hash of the highest numbered version held.
+    pub latest: Option<Hash>,
+    /// Missing predecessors, in version number order.
+    pub gaps: Vec<VersionGap>,
+    /// Forks, in version number order.
+    pub forks: Vec<VersionFork>,
+}
+
+impl VersionChainReport {
+    /// Check the manifests held for a chain.
+    fn from_manifests(root: Hash, manifests: &[Manifest]) -> Self {
+        let held: HashSet<Hash> = manifests.iter().map(|m| m.hash).collect();
+        let mut ordered: Vec<&Manifest> = manifests.iter().collect();
+        ordered.sort_by_key(|m| (m.version.number, m.hash.0));
+
+        let mut gaps = Vec::new();
+        // Predecessors in order of their first successor's version number
+        let mut successors: Vec<(Hash, Vec<Hash>)> = Vec::new();
+        for manifest in &ordered {
+            let Some(previous) = manifest.version.previous else {
+                continue;
+            };
+            if !held.contains(&previous) {
+                gaps.push(VersionGap {
+                    hash: manifest.hash,
+                    number: manifest.version.number,
+                    missing: previous,
+                });
+            }
+            match successors.iter_mut().find(|(p, _)| *p == previous) {
+                Some((_, branches)) => branches.push(manifest.hash),
+                None => successors.push((previous, vec![manifest.hash])),
+            }
+        }
+
+        let forks = successors
+            .into_iter()
+            .filter(|(_, branches)| branches.len() > 1)
+            .map(|(previous, branches)| VersionFork { previous, branches })
+            .collect();
+
+        Self {
+            root,
+            versions: manifests.len(),
+            latest: ordered.last().map(|m| m.hash),
+            gaps,
+            forks,
+        }
+    }
+
+    /// Whether the chain has neither gaps nor forks.
+    pub fn is_intact(&self) -> bool {
+        self.gaps.is_empty() && self.forks.is_empty()
+    }
+}
+
+/// Result of repairing a version chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionChainRepair {
+    /// Manifests fetched from the owner and stored.
+    pub fetched: Vec<Hash>,
+    /// The chain after the repair.
+    pub report: VersionChainReport,
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Check the version chain containing `hash` for gaps and forks.
+    ///
+    /// `hash` may be any version held, or the version root.
+    pub fn verify_version_chain(&self, hash: &Hash) -> OpsResult<VersionChainReport> {
+        let root = self.version_root_of(hash)?;
+        let manifests = self.state.manifests.get_versions(&root)?;
+        if manifests.is_empty() {
+            return Err(OpsError::ManifestNotFound(*hash));
+        }
+        Ok(VersionChainReport::from_manifests(root, &manifests))
+    }
+
+    /// Fill gaps in the version chain containing `hash` from its owner.
+    ///
+    /// 1. Verifies the chain; an intact chain is returned as is
+    /// 2. Asks the owner for the chain's manifests in a VERSION_REQUEST
+    /// 3. Stores each missing predecessor the owner returned, following
+    ///    `previous` links back until one is held or not returned
+    /// 4. Verifies the chain again
+    ///
+    /// Only manifests with the chain's root and owner are accepted. Gaps
+    /// the owner couldn't fill (versions it keeps private, or no longer
+    /// holds) remain in the report. Forks are reported but never resolved.
+    pub async fn repair_version_chain(&self, hash: &Hash) -> OpsResult<VersionChainRepair> {
+        // 1. Verify
+        let report = self.verify_version_chain(hash)?;
+        if report.gaps.is_empty() {
+            return Ok(VersionChainRepair {
+                fetched: Vec::new(),
+                report,
+            });
+        }
+        let owner = self
+            .state
+            .manifests
+            .get_versions(&report.root)?
+            .first()
+            .map(|m| m.owner)
+            .ok_or(OpsError::ManifestNotFound(*hash))?;
+        if owner == self.peer_id() {
+            // Nobody else holds our own history
+            return Ok(VersionChainRepair {
+                fetched: Vec::new(),
+                report,
+            });
+        }
+
+        // 2. Ask the owner
+        let network = self.network().cloned().ok_or_else(|| {
+            OpsError::invalid_operation("network required to repair a version chain")
+        })?;
+        let libp2p_peer = network
+            .libp2p_peer_id(&owner)
+            .ok_or(OpsError::PeerIdNotFound)?;
+        let response = network
+            .send_version_request(
+                libp2p_peer,
+                VersionRequestPayload {
+                    version_root: report.root,
+                    delta_from: None,
+                    include_manifests: true,
+                },
+            )
+            .await?;
+        let mut offered: HashMap<Hash, Manifest> = response
+            .manifests
+            .into_iter()
+            .filter(|m| m.version.root == report.root && m.owner == owner)
+            .map(|m| (m.hash, m))
+            .collect();
+
+        // 3. Store missing predecessors
+        let mut fetched = Vec::new();
+        let mut wanted: Vec<Hash> = report.gaps.iter().map(|gap| gap.missing).collect();
+        while let Some(missing) = wanted.pop() {
+            if self.state.manifests.load(&missing)?.is_some() {
+                continue;
+            }
+            let Some(mut manifest) = offered.remove(&missing) else {
+                continue;
+            };
+            self.check_remote_metadata(&mut manifest);
+            if let Err(e) = self.screen_remote_content(&manifest) {
+                warn!(hash = %missing, error = %e, "Rejected version manifest");
+                continue;
+            }
+            self.store_remote_manifest(&manifest)?;
+            fetched.push(missing);
+            if let Some(previous) = manifest.version.previous {
+                wanted.push(previous);
+            }
+        }
+        if !fetched.is_empty() {
+            info!(root = %report.root, fetched = fetched.len(), "Repaired version chain");
+        }
+
+        // 4. Verify again
+        Ok(VersionChainRepair {
+            fetched,
+            report: self.verify_version_chain(&report.root)?,
+        })
+    }
+
+    /// Warn if the manifests held for a chain don't form an intact chain.
+    pub(crate) fn warn_on_broken_chain(&self, root: &Hash, manifests: &[Manifest]) {
+        let report = VersionChainReport::from_manifests(*root, manifests);
+        if !report.is_intact() {
+            warn!(
+                root = %root,
+                gaps = report.gaps.len(),
+                forks = report.forks.len(),
+                "Version history is incomplete"
+            );
+        }
+    }
+
+    /// The version root of `hash`, or `hash` itself if we hold no manifest
+    /// for it.
+    fn version_root_of(&self, hash: &Hash) -> OpsResult<Hash> {
+        Ok(self
+            .state
+            .manifests
+            .load(hash)?
+            .map_or(*hash, |m| m.version.root))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OpsConfig;
+    use crate::node_ops::DefaultNodeOperations;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use nodalync_types::Metadata;
+    use tempfile::TempDir;
+
+    fn create_test_ops() -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (_, public_key) = generate_identity();
+        let ops = DefaultNodeOperations::with_config(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default(),
+        );
+        (ops, temp_dir)
+    }
+
+    fn create_chain(ops: &DefaultNodeOperations, len: usize) -> Vec<Hash> {
+        let mut hashes = vec![ops
+            .create_content(b"Version 1", Metadata::new("Doc", 9))
+            .unwrap()];
+        for n in 2..=len {
+            let content = format!("Version {}", n);
+            let previous = *hashes.last().unwrap();
+            hashes.push(
+                ops.update_content(
+                    &previous,
+                    content.as_bytes(),
+                    Metadata::new("Doc", content.len() as u64),
+                )
+                .unwrap(),
+            );
+        }
+        hashes
+    }
+
+    #[test]
+    fn test_verify_intact_chain() {
+        let (ops, _temp) = create_test_ops();
+        let hashes = create_chain(&ops, 3);
+
+        let report = ops.verify_version_chain(&hashes[1]).unwrap();
+        assert_eq!(report.root, hashes[0]);
+        assert_eq!(report.versions, 3);
+        assert_eq!(report.latest, Some(hashes[2]));
+        assert!(report.is_intact());
+    }
+
+    #[test]
+    fn test_verify_detects_gaps_and_forks() {
+        let (ops, _temp) = create_test_ops();
+        let hashes = create_chain(&ops, 4);
+
+        // Lose v2
+        ops.state.manifests.delete(&hashes[1]).unwrap();
+        let report = ops.verify_version_chain(&hashes[3]).unwrap();
+        assert_eq!(
+            report.gaps,
+            vec![VersionGap {
+                hash: hashes[2],
+                number: 3,
+                missing: hashes[1],
+            }]
+        );
+        assert!(report.forks.is_empty());
+
+        // A second v4 branching off v3
+        let branch = ops
+            .update_content(&hashes[2], b"Version 4b", Metadata::new("Doc", 10))
+            .unwrap();
+        let report = ops.verify_version_chain(&hashes[0]).unwrap();
+        assert_eq!(report.forks.len(), 1);
+        assert_eq!(report.forks[0].previous, hashes[2]);
+        assert_eq!(report.forks[0].branches.len(), 2);
+        assert!(report.forks[0].branches.contains(&branch));
+        assert!(!report.is_intact());
+    }
+
+    #[test]
+    fn test_verify_unknown_hash() {
+        let (ops, _temp) = create_test_ops();
+        let unknown = nodalync_crypto::content_hash(b"unknown");
+        assert!(matches!(
+            ops.verify_version_chain(&unknown),
+            Err(OpsError::ManifestNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_repair_own_chain_is_a_no_op() {
+        let (ops, _temp) = create_test_ops();
+        let hashes = create_chain(&ops, 3);
+        ops.state.manifests.delete(&hashes[1]).unwrap();
+
+        let repair = ops.repair_version_chain(&hashes[2]).await.unwrap();
+        assert!(repair.fetched.is_empty());
+        assert_eq!(repair.report.gaps.len(), 1);
+    }
+}
@@ -0,0 +1,326 @@
+//! Wallet operations.
+//!
+//! This module provides the read and write APIs behind a wallet view:
+//! settlement balances, deposits and withdrawals, recent on-chain
+//! transactions, the pending settlement queue, and per-content earnings.
+//!
+//! All on-chain calls go through the configured [`Settlement`] trait object
+//! so frontends never talk to Hedera directly.
+//!
+//! [`Settlement`]: nodalync_settle::Settlement
+
+use nodalync_crypto::Hash;
+use nodalync_store::{
+    LedgerAccount, LedgerEvent, ManifestFilter, ManifestStore, QueuedDistribution,
+    SettlementQueueStore, WalletTransaction, WalletTransactionKind,
+};
+use nodalync_types::Amount;
+use nodalync_valid::Validator;
+use tracing::info;
+
+use crate::error::{OpsError, OpsResult};
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+/// Snapshot of the node's wallet state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletSummary {
+    /// Hedera account used for settlement (if settlement is configured).
+    pub account_id: Option<String>,
+    /// Balance deposited in the settlement contract (if settlement is configured).
+    pub contract_balance: Option<Amount>,
+    /// On-chain account balance (if settlement is configured).
+    pub account_balance: Option<Amount>,
+    /// Total amount waiting in the settlement queue.
+    pub pending_settlement_total: Amount,
+    /// Number of distributions waiting in the settlement queue.
+    pub pending_settlement_count: u64,
+    /// Total revenue recorded across all owned content.
+    pub total_earned: Amount,
+}
+
+/// Earnings for a single piece of owned content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentEarnings {
+    /// Content hash.
+    pub hash: Hash,
+    /// Content title.
+    pub title: String,
+    /// Current price per query.
+    pub price: Amount,
+    /// Number of paid queries served.
+    pub total_queries: u64,
+    /// Total revenue recorded for this content.
+    pub total_revenue: Amount,
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Build a wallet summary.
+    ///
+    /// On-chain balances are `None` when no settlement is configured.
+    pub async fn wallet_summary(&self) -> OpsResult<WalletSummary> {
+        let (account_id, contract_balance, account_balance) = match self.settlement() {
+            Some(settlement) => {
+                let contract = settlement
+                    .get_balance()
+                    .await
+                    .map_err(|e| OpsError::SettlementFailed(e.to_string()))?;
+                let account = settlement
+                    .get_account_balance()
+                    .await
+                    .map_err(|e| OpsError::SettlementFailed(e.to_string()))?;
+                (
+                    Some(settlement.get_own_account_string()),
+                    Some(contract),
+                    Some(account),
+                )
+            }
+            None => (None, None, None),
+        };
+
+        let total_earned = self
+            .content_earnings(None)?
+            .iter()
+            .map(|e| e.total_revenue)
+            .fold(0, Amount::saturating_add);
+
+        Ok(WalletSummary {
+            account_id,
+            contract_balance,
+            account_balance,
+            pending_settlement_total: self.state.settlement.get_pending_total()?,
+            pending_settlement_count: self.state.settlement.pending_count()?,
+            total_earned,
+        })
+    }
+
+    /// Deposit into the settlement contract.
+    ///
+    /// Records the transaction in the local wallet history and returns its ID.
+    pub async fn wallet_deposit(&self, amount: Amount) -> OpsResult<String> {
+        if amount == 0 {
+            return Err(OpsError::invalid_operation("deposit amount must be > 0"));
+        }
+        let settlement = self
+            .settlement()
+            .cloned()
+            .ok_or(OpsError::SettlementRequired)?;
+
+        let tx_id = settlement
+            .deposit(amount)
+            .await
+            .map_err(|e| OpsError::SettlementFailed(e.to_string()))?
+            .to_string();
+        info!(amount, tx_id = %tx_id, "Deposited into settlement contract");
+
+        self.state
+            .settlement
+            .record_transaction(&WalletTransaction::new(
+                tx_id.clone(),
+                WalletTransactionKind::Deposit,
+                amount,
+                current_timestamp(),
+            ))?;
+        self.record_ledger_transfer(
+            LedgerEvent::ContractDeposit,
+            &tx_id,
+            LedgerAccount::External,
+            LedgerAccount::Contract,
+            amount,
+        );
+        Ok(tx_id)
+    }
+
+    /// Withdraw from the settlement contract.
+    ///
+    /// Records the transaction in the local wallet history and returns its ID.
+    pub async fn wallet_withdraw(&self, amount: Amount) -> OpsResult<String> {
+        if amount == 0 {
+            return Err(OpsError::invalid_operation("withdraw amount must be > 0"));
+        }
+        let settlement = self
+            .settlement()
+            .cloned()
+            .ok_or(OpsError::SettlementRequired)?;
+
+        let tx_id = settlement
+            .withdraw(amount)
+            .await
+            .map_err(|e| OpsError::SettlementFailed(e.to_string()))?
+            .to_string();
+        info!(amount, tx_id = %tx_id, "Withdrew from settlement contract");
+
+        self.state
+            .settlement
+            .record_transaction(&WalletTransaction::new(
+                tx_id.clone(),
+                WalletTransactionKind::Withdraw,
+                amount,
+                current_timestamp(),
+            ))?;
+        self.record_ledger_transfer(
+            LedgerEvent::ContractWithdrawal,
+            &tx_id,
+            LedgerAccount::Contract,
+            LedgerAccount::External,
+            amount,
+        );
+        Ok(tx_id)
+    }
+
+    /// Get recent on-chain transactions submitted by this node, newest first.
+    pub fn recent_wallet_transactions(&self, limit: u32) -> OpsResult<Vec<WalletTransaction>> {
+        Ok(self.state.settlement.recent_transactions(limit)?)
+    }
+
+    /// Get all distributions waiting in the settlement queue.
+    pub fn pending_settlements(&self) -> OpsResult<Vec<QueuedDistribution>> {
+        Ok(self.state.settlement.get_pending()?)
+    }
+
+    /// Get per-content earnings for owned content, highest revenue first.
+    ///
+    /// Content that has never been queried is omitted.
+    pub fn content_earnings(&self, limit: Option<usize>) -> OpsResult<Vec<ContentEarnings>> {
+        let filter = ManifestFilter::new().with_owner(self.peer_id());
+        let mut earnings: Vec<ContentEarnings> = self
+            .state
+            .manifests
+            .list(filter)?
+            .into_iter()
+            .filter(|m| m.economics.total_queries > 0 || m.economics.total_revenue > 0)
+            .map(|m| ContentEarnings {
+                hash: m.hash,
+                title: m.metadata.title,
+                price: m.economics.price,
+                total_queries: m.economics.total_queries,
+                total_revenue: m.economics.total_revenue,
+            })
+            .collect();
+
+        earnings.sort_by(|a, b| b.total_revenue.cmp(&a.total_revenue));
+        if let Some(limit) = limit {
+            earnings.truncate(limit);
+        }
+        Ok(earnings)
+    }
+
+    /// Record a settlement batch transaction in the wallet history.
+    pub(crate) fn record_settlement_transaction(&self, tx_id: &str, amount: Amount) {
+        let tx = WalletTransaction::new(
+            tx_id,
+            WalletTransactionKind::Settlement,
+            amount,
+            current_timestamp(),
+        );
+        if let Err(e) = self.state.settlement.record_transaction(&tx) {
+            tracing::warn!(tx_id = %tx_id, error = %e, "Failed to record settlement transaction");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_test_utils::*;
+    use nodalync_types::{Metadata, Visibility};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_wallet_summary_without_settlement() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let state =
+            nodalync_store::NodeState::open(nodalync_store::NodeStateConfig::new(temp.path()))
+                .unwrap();
+        let (_, pk) = generate_identity();
+        let ops = crate::DefaultNodeOperations::with_defaults(state, peer_id_from_public_key(&pk));
+
+        let summary = ops.wallet_summary().await.unwrap();
+        assert!(summary.account_id.is_none());
+        assert!(summary.contract_balance.is_none());
+        assert_eq!(summary.pending_settlement_total, 0);
+        assert_eq!(summary.total_earned, 0);
+    }
+
+    #[tokio::test]
+    async fn test_wallet_deposit_and_withdraw_recorded() {
+        let mock = MockSettlement::new().with_balance(1000);
+        let (ops, _temp) = create_test_ops_with_settlement(Arc::new(mock.clone()));
+
+        let deposit_tx = ops.wallet_deposit(500).await.unwrap();
+        let withdraw_tx = ops.wallet_withdraw(200).await.unwrap();
+
+        assert_eq!(mock.deposits(), vec![500]);
+        assert_eq!(mock.withdrawals(), vec![200]);
+
+        let history = ops.recent_wallet_transactions(10).unwrap();
+        assert_eq!(history.len(), 2);
+        let ids: Vec<_> = history.iter().map(|t| t.transaction_id.clone()).collect();
+        assert!(ids.contains(&deposit_tx));
+        assert!(ids.contains(&withdraw_tx));
+
+        let summary = ops.wallet_summary().await.unwrap();
+        assert_eq!(summary.contract_balance, Some(1300));
+        assert!(summary.account_id.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_wallet_deposit_requires_settlement() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let state =
+            nodalync_store::NodeState::open(nodalync_store::NodeStateConfig::new(temp.path()))
+                .unwrap();
+        let (_, pk) = generate_identity();
+        let ops = crate::DefaultNodeOperations::with_defaults(state, peer_id_from_public_key(&pk));
+
+        let result = ops.wallet_deposit(100).await;
+        assert!(matches!(result, Err(OpsError::SettlementRequired)));
+    }
+
+    #[tokio::test]
+    async fn test_content_earnings_and_pending() {
+        let (ops, _temp) = create_test_ops_with_settlement(Arc::new(MockSettlement::new()));
+
+        let hash = ops
+            .create_content(b"earning content", Metadata::new("Earner", 15))
+            .unwrap();
+        ops.publish_content(&hash, Visibility::Shared, 100)
+            .await
+            .unwrap();
+        let _idle = ops
+            .create_content(b"idle content", Metadata::new("Idle", 12))
+            .unwrap();
+
+        let mut manifest = ops.state.manifests.load(&hash).unwrap().unwrap();
+        manifest.economics.record_query(100);
+        ops.state.manifests.update(&manifest).unwrap();
+
+        ops.state
+            .settlement
+            .enqueue(QueuedDistribution::new(
+                content_hash(b"payment"),
+                ops.peer_id(),
+                100,
+                hash,
+                current_timestamp(),
+            ))
+            .unwrap();
+
+        let earnings = ops.content_earnings(None).unwrap();
+        assert_eq!(earnings.len(), 1);
+        assert_eq!(earnings[0].hash, hash);
+        assert_eq!(earnings[0].total_revenue, 100);
+
+        assert_eq!(ops.pending_settlements().unwrap().len(), 1);
+
+        let summary = ops.wallet_summary().await.unwrap();
+        assert_eq!(summary.total_earned, 100);
+        assert_eq!(summary.pending_settlement_total, 100);
+        assert_eq!(summary.pending_settlement_count, 1);
+    }
+}
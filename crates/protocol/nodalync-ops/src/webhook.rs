@@ -0,0 +1,533 @@
+//! Webhooks for economic and content events.
+//!
+//! Operators register endpoint URLs in `WebhookConfig` for the events they
+//! care about (payments received, content queried, settlements confirmed,
+//! channels disputed). When one of those events is emitted on the event
+//! bus, a JSON notification is queued in the store once per endpoint.
+//! `deliver_webhooks` posts due notifications through a
+//! [`WebhookTransport`] (the CLI provides an HTTP one), retrying failures
+//! with exponential backoff and recording each delivery's status.
+//!
+//! Every post is signed with the endpoint's secret: the
+//! `X-Nodalync-Signature` header carries `sha256=` and the hex HMAC-SHA256
+//! of `"{timestamp}.{body}"`, where the timestamp is the
+//! `X-Nodalync-Timestamp` header. Receivers recompute it to check the
+//! post came from this node and reject old timestamps to stop replays.
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use nodalync_store::{WebhookDelivery, WebhookDeliveryStatus, WebhookStore};
+use nodalync_valid::Validator;
+use serde_json::json;
+use sha2::Sha256;
+use tracing::{debug, info, warn};
+
+use crate::config::WebhookEvent;
+use crate::error::OpsResult;
+use crate::events::OpsEvent;
+use crate::extraction::L1Extractor;
+use crate::node_ops::{current_timestamp, NodeOperations};
+
+/// Header naming the event type.
+pub const EVENT_HEADER: &str = "X-Nodalync-Event";
+/// Header carrying the delivery ID, the same across retries.
+pub const DELIVERY_HEADER: &str = "X-Nodalync-Delivery";
+/// Header carrying the signing timestamp (ms).
+pub const TIMESTAMP_HEADER: &str = "X-Nodalync-Timestamp";
+/// Header carrying the body signature.
+pub const SIGNATURE_HEADER: &str = "X-Nodalync-Signature";
+
+/// A signed notification ready to post.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookRequest {
+    /// Endpoint URL.
+    pub url: String,
+    /// Event type.
+    pub event: String,
+    /// Delivery ID.
+    pub delivery_id: u64,
+    /// When the request was signed (ms).
+    pub timestamp: u64,
+    /// `sha256=` followed by the hex HMAC of the timestamp and body.
+    pub signature: String,
+    /// JSON body.
+    pub body: String,
+}
+
+impl WebhookRequest {
+    /// Headers to send along with the JSON body.
+    pub fn headers(&self) -> [(&'static str, String); 4] {
+        [
+            (EVENT_HEADER, self.event.clone()),
+            (DELIVERY_HEADER, self.delivery_id.to_string()),
+            (TIMESTAMP_HEADER, self.timestamp.to_string()),
+            (SIGNATURE_HEADER, self.signature.clone()),
+        ]
+    }
+}
+
+/// Posts webhook requests to their endpoints.
+#[async_trait]
+pub trait WebhookTransport: Send + Sync {
+    /// Post the request. `Ok` means the endpoint accepted it (2xx);
+    /// the error is recorded against the delivery.
+    async fn post(&self, request: &WebhookRequest) -> Result<(), String>;
+}
+
+/// Sign a webhook body for `timestamp` with `secret`.
+///
+/// Returns `sha256=` followed by the hex HMAC-SHA256 of
+/// `"{timestamp}.{body}"`.
+pub fn sign_webhook(secret: &str, timestamp: u64, body: &str) -> String {
+    // HMAC takes keys of any length
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    let hex: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("sha256={}", hex)
+}
+
+/// Check a signature made by [`sign_webhook`].
+pub fn verify_webhook(secret: &str, timestamp: u64, body: &str, signature: &str) -> bool {
+    let expected = sign_webhook(secret, timestamp, body);
+    // Compare without stopping at the first difference
+    expected.len() == signature.len()
+        && expected
+            .bytes()
+            .zip(signature.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Outcome of a delivery run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WebhookDeliveryReport {
+    /// Deliveries accepted by their endpoint.
+    pub delivered: usize,
+    /// Deliveries that failed and will be retried.
+    pub retrying: usize,
+    /// Deliveries given up on.
+    pub failed: usize,
+    /// Finished deliveries pruned from the history.
+    pub pruned: usize,
+}
+
+impl<V, E> NodeOperations<V, E>
+where
+    V: Validator,
+    E: L1Extractor,
+{
+    /// Queue an event for every endpoint registered for it.
+    ///
+    /// Called for every emitted event; events webhooks don't carry are
+    /// ignored. Failures to queue are logged, not returned, so the
+    /// operation that emitted the event is unaffected.
+    pub(crate) fn queue_webhooks(&self, event: &OpsEvent) {
+        let config = &self.config.webhooks;
+        if !config.is_enabled() {
+            return;
+        }
+        let Some((kind, data)) = webhook_data(event) else {
+            return;
+        };
+
+        let now = current_timestamp();
+        let body = json!({
+            "event": kind.as_str(),
+            "node": self.peer_id().to_string(),
+            "timestamp": now,
+            "data": data,
+        })
+        .to_string();
+
+        for endpoint in config.endpoints.iter().filter(|e| e.wants(kind)) {
+            match self
+                .state
+                .webhooks
+                .enqueue(&endpoint.url, kind.as_str(), &body, now)
+            {
+                Ok(id) => debug!(id, url = %endpoint.url, event = %kind, "Queued webhook"),
+                Err(e) => {
+                    warn!(url = %endpoint.url, event = %kind, error = %e, "Failed to queue webhook")
+                }
+            }
+        }
+    }
+
+    /// Post due webhook notifications.
+    ///
+    /// Returns `None` when no webhook transport is set. Failed posts are
+    /// retried with backoff until `max_attempts` is reached, and finished
+    /// deliveries older than `history_ttl_secs` are pruned.
+    pub async fn deliver_webhooks(&self) -> OpsResult<Option<WebhookDeliveryReport>> {
+        let Some(transport) = self.webhook_transport().cloned() else {
+            return Ok(None);
+        };
+        let config = self.config.webhooks.clone();
+        let timeout = std::time::Duration::from_millis(config.timeout_ms);
+        let mut report = WebhookDeliveryReport::default();
+
+        let due = self
+            .state
+            .webhooks
+            .due(current_timestamp(), config.batch_size)?;
+        for delivery in due {
+            let Some(endpoint) = config.endpoints.iter().find(|e| e.url == delivery.url) else {
+                // Removed from the config since it was queued
+                self.state.webhooks.record_failure(
+                    delivery.id,
+                    "endpoint no longer registered",
+                    current_timestamp(),
+                    None,
+                )?;
+                report.failed += 1;
+                continue;
+            };
+
+            let timestamp = current_timestamp();
+            let request = WebhookRequest {
+                signature: sign_webhook(&endpoint.secret, timestamp, &delivery.payload),
+                url: delivery.url.clone(),
+                event: delivery.event.clone(),
+                delivery_id: delivery.id,
+                timestamp,
+                body: delivery.payload.clone(),
+            };
+            let result = match tokio::time::timeout(timeout, transport.post(&request)).await {
+                Ok(result) => result,
+                Err(_) => Err(format!("timed out after {} ms", config.timeout_ms)),
+            };
+
+            let now = current_timestamp();
+            match result {
+                Ok(()) => {
+                    self.state.webhooks.mark_delivered(delivery.id, now)?;
+                    debug!(id = delivery.id, url = %delivery.url, "Webhook delivered");
+                    report.delivered += 1;
+                }
+                Err(error) => {
+                    let attempts = delivery.attempts + 1;
+                    let retry_at = config
+                        .retry_delay_ms(attempts)
+                        .map(|delay| now.saturating_add(delay));
+                    self.state
+                        .webhooks
+                        .record_failure(delivery.id, &error, now, retry_at)?;
+                    if retry_at.is_some() {
+                        debug!(id = delivery.id, url = %delivery.url, attempts, error = %error, "Webhook delivery failed, will retry");
+                        report.retrying += 1;
+                    } else {
+                        warn!(id = delivery.id, url = %delivery.url, attempts, error = %error, "Webhook delivery failed, giving up");
+                        report.failed += 1;
+                    }
+                }
+            }
+        }
+
+        let cutoff = current_timestamp().saturating_sub(config.history_ttl_secs * 1000);
+        report.pruned = self.state.webhooks.prune(cutoff)?;
+
+        if report.delivered + report.retrying + report.failed > 0 {
+            info!(
+                delivered = report.delivered,
+                retrying = report.retrying,
+                failed = report.failed,
+                "Webhook delivery run finished"
+            );
+        }
+        Ok(Some(report))
+    }
+
+    /// List webhook deliveries newest first, optionally filtered by status.
+    pub fn webhook_deliveries(
+        &self,
+        status: Option<WebhookDeliveryStatus>,
+        limit: u32,
+    ) -> OpsResult<Vec<WebhookDelivery>> {
+        Ok(self.state.webhooks.list(status, limit)?)
+    }
+}
+
+/// The webhook event type and data for an operations event, if webhooks
+/// carry it.
+fn webhook_data(event: &OpsEvent) -> Option<(WebhookEvent, serde_json::Value)> {
+    let data = match event {
+        OpsEvent::PaymentReceived {
+            payment_id,
+            payer,
+            content_hash,
+            amount,
+            transaction_id,
+        } => (
+            WebhookEvent::PaymentReceived,
+            json!({
+                "payment_id": payment_id.to_string(),
+                "payer": payer.to_string(),
+                "content_hash": content_hash.to_string(),
+                "amount": amount,
+                "transaction_id": transaction_id,
+            }),
+        ),
+        OpsEvent::ContentQueried {
+            content_hash,
+            requester,
+            amount,
+        } => (
+            WebhookEvent::ContentQueried,
+            json!({
+                "content_hash": content_hash.to_string(),
+                "requester": requester.to_string(),
+                "amount": amount,
+            }),
+        ),
+        OpsEvent::SettlementConfirmed {
+            batch_id,
+            transaction_id,
+            amount,
+        } => (
+            WebhookEvent::SettlementConfirmed,
+            json!({
+                "batch_id": batch_id.to_string(),
+                "transaction_id": transaction_id,
+                "amount": amount,
+            }),
+        ),
+        OpsEvent::ChannelDisputed {
+            peer,
+            channel_id,
+            transaction_id,
+        } => (
+            WebhookEvent::ChannelDisputed,
+            json!({
+                "peer": peer.to_string(),
+                "channel_id": channel_id.to_string(),
+                "transaction_id": transaction_id,
+            }),
+        ),
+        OpsEvent::DisputeOpened {
+            peer,
+            channel_id,
+            disputed_nonce,
+            our_nonce,
+            deadline,
+        } => (
+            WebhookEvent::DisputeOpened,
+            json!({
+                "peer": peer.to_string(),
+                "channel_id": channel_id.to_string(),
+                "disputed_nonce": disputed_nonce,
+                "our_nonce": our_nonce,
+                "deadline": deadline,
+            }),
+        ),
+        OpsEvent::DisputeDeadlineApproaching {
+            peer,
+            channel_id,
+            deadline,
+            countered,
+        } => (
+            WebhookEvent::DisputeDeadline,
+            json!({
+                "peer": peer.to_string(),
+                "channel_id": channel_id.to_string(),
+                "deadline": deadline,
+                "countered": countered,
+            }),
+        ),
+        OpsEvent::DisputeCountered {
+            peer,
+            channel_id,
+            nonce,
+            transaction_id,
+        } => (
+            WebhookEvent::DisputeCountered,
+            json!({
+                "peer": peer.to_string(),
+                "channel_id": channel_id.to_string(),
+                "nonce": nonce,
+                "transaction_id": transaction_id,
+            }),
+        ),
+        OpsEvent::ContentUnreachable {
+            hash,
+            checker,
+            consecutive_failures,
+            error,
+        } => (
+            WebhookEvent::ContentUnreachable,
+            json!({
+                "content_hash": hash.to_string(),
+                "checker": checker.to_string(),
+                "consecutive_failures": consecutive_failures,
+                "error": error,
+            }),
+        ),
+        _ => return None,
+    };
+    Some(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{OpsConfig, WebhookConfig, WebhookEndpoint};
+    use crate::DefaultNodeOperations;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_store::{NodeState, NodeStateConfig};
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
+
+    /// Records requests, failing the first `failures` posts.
+    #[derive(Default)]
+    struct RecordingTransport {
+        requests: Mutex<Vec<WebhookRequest>>,
+        failures: Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl WebhookTransport for RecordingTransport {
+        async fn post(&self, request: &WebhookRequest) -> Result<(), String> {
+            self.requests.lock().unwrap().push(request.clone());
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err("HTTP 503".to_string());
+            }
+            Ok(())
+        }
+    }
+
+    fn create_ops(webhooks: WebhookConfig) -> (DefaultNodeOperations, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state = NodeState::open(NodeStateConfig::new(temp_dir.path())).unwrap();
+        let (_, public_key) = generate_identity();
+        let ops = DefaultNodeOperations::with_config(
+            state,
+            peer_id_from_public_key(&public_key),
+            OpsConfig::default().with_webhooks(webhooks),
+        );
+        (ops, temp_dir)
+    }
+
+    fn payment_event() -> OpsEvent {
+        OpsEvent::PaymentReceived {
+            payment_id: content_hash(b"payment"),
+            payer: nodalync_crypto::PeerId::from_bytes([3u8; 20]),
+            content_hash: content_hash(b"content"),
+            amount: 500,
+            transaction_id: Some("0.0.1@1.2".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signature = sign_webhook("secret", 1_000, "{}");
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), "sha256=".len() + 64);
+        assert!(verify_webhook("secret", 1_000, "{}", &signature));
+        assert!(!verify_webhook("other", 1_000, "{}", &signature));
+        assert!(!verify_webhook("secret", 1_001, "{}", &signature));
+        assert!(!verify_webhook("secret", 1_000, "{ }", &signature));
+    }
+
+    #[test]
+    fn test_queue_only_for_subscribed_endpoints() {
+        let (ops, _temp) = create_ops(
+            WebhookConfig::default()
+                .with_endpoint(WebhookEndpoint::new("https://all.example", "a"))
+                .with_endpoint(
+                    WebhookEndpoint::new("https://disputes.example", "b")
+                        .with_events(vec![WebhookEvent::ChannelDisputed]),
+                ),
+        );
+
+        ops.emit(payment_event());
+        ops.emit(OpsEvent::SettlementToppedUp {
+            amount: 1,
+            balance: 2,
+        });
+
+        let deliveries = ops.webhook_deliveries(None, 10).unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].url, "https://all.example");
+        assert_eq!(deliveries[0].event, "payment_received");
+
+        let body: serde_json::Value = serde_json::from_str(&deliveries[0].payload).unwrap();
+        assert_eq!(body["event"], "payment_received");
+        assert_eq!(body["node"], ops.peer_id().to_string());
+        assert_eq!(body["data"]["amount"], 500);
+        assert_eq!(body["data"]["transaction_id"], "0.0.1@1.2");
+    }
+
+    #[tokio::test]
+    async fn test_deliver_signs_and_marks_delivered() {
+        let (mut ops, _temp) = create_ops(
+            WebhookConfig::default()
+                .with_endpoint(WebhookEndpoint::new("https://hooks.example", "secret")),
+        );
+        assert_eq!(ops.deliver_webhooks().await.unwrap(), None);
+
+        let transport = Arc::new(RecordingTransport::default());
+        ops.set_webhook_transport(transport.clone());
+        ops.emit(payment_event());
+
+        let report = ops.deliver_webhooks().await.unwrap().unwrap();
+        assert_eq!(report.delivered, 1);
+
+        let requests = transport.requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        assert!(verify_webhook(
+            "secret",
+            request.timestamp,
+            &request.body,
+            &request.signature
+        ));
+        assert_eq!(
+            request.headers()[0],
+            (EVENT_HEADER, "payment_received".to_string())
+        );
+
+        let delivery = ops
+            .state
+            .webhooks
+            .get(request.delivery_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Delivered);
+    }
+
+    #[tokio::test]
+    async fn test_failed_delivery_backs_off_then_gives_up() {
+        let (mut ops, _temp) = create_ops(
+            WebhookConfig::default()
+                .with_endpoint(WebhookEndpoint::new("https://hooks.example", "secret"))
+                .with_max_attempts(2)
+                .with_backoff(0, 0),
+        );
+        let transport = Arc::new(RecordingTransport::default());
+        *transport.failures.lock().unwrap() = 2;
+        ops.set_webhook_transport(transport.clone());
+        ops.emit(payment_event());
+
+        let report = ops.deliver_webhooks().await.unwrap().unwrap();
+        assert_eq!(report.retrying, 1);
+        let delivery = &ops.webhook_deliveries(None, 1).unwrap()[0];
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Pending);
+        assert_eq!(delivery.last_error.as_deref(), Some("HTTP 503"));
+
+        let report = ops.deliver_webhooks().await.unwrap().unwrap();
+        assert_eq!(report.failed, 1);
+        let delivery = &ops.webhook_deliveries(None, 1).unwrap()[0];
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Failed);
+        assert_eq!(delivery.attempts, 2);
+        assert_eq!(transport.requests.lock().unwrap().len(), 2);
+    }
+}
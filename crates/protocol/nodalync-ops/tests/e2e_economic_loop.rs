@@ -168,9 +168,17 @@ async fn test_e2e_simple_l0_publish_query_settle() {
     let query_request = QueryRequestPayload {
         hash,
         query: None,
-        payment,
+        payment: Some(payment),
         version_spec: None,
         payment_nonce: 1,
+        capability: None,
+        recipient_key: None,
+        challenge_response: None,
+        request_id: None,
+        section: None,
+        unit: None,
+        referrer: None,
+        entitlement: None,
     };
 
     // Simulate Bob sending query to Alice
@@ -220,7 +228,7 @@ async fn test_e2e_simple_l0_publish_query_settle() {
 #[tokio::test]
 async fn test_e2e_multihop_provenance_distribution() {
     // === SETUP ===
-    let mut alice = TestNode::new();
+    let alice = TestNode::new();
     let mut bob = TestNode::new();
     let carol = TestNode::new();
 
@@ -269,6 +277,7 @@ async fn test_e2e_multihop_provenance_distribution() {
         )],
         derived_from: vec![l0_hash],
         depth: 1,
+        attested: false,
     };
 
     let l3_manifest = Manifest {
@@ -280,6 +289,7 @@ async fn test_e2e_multihop_provenance_distribution() {
             previous: None,
             root: l3_hash,
             timestamp: current_timestamp(),
+            forked: false,
         },
         visibility: Visibility::Shared,
         access: nodalync_types::AccessControl::default(),
@@ -289,6 +299,9 @@ async fn test_e2e_multihop_provenance_distribution() {
             currency: nodalync_types::Currency::HBAR,
             total_queries: 0,
             total_revenue: 0,
+            bond: None,
+            pricing: None,
+            referral: None,
         },
         provenance: l3_provenance.clone(),
         created_at: current_timestamp(),
@@ -311,9 +324,17 @@ async fn test_e2e_multihop_provenance_distribution() {
     let query_request = QueryRequestPayload {
         hash: l3_hash,
         query: None,
-        payment,
+        payment: Some(payment),
         version_spec: None,
         payment_nonce: 1,
+        capability: None,
+        recipient_key: None,
+        challenge_response: None,
+        request_id: None,
+        section: None,
+        unit: None,
+        referrer: None,
+        entitlement: None,
     };
 
     let response = bob
@@ -402,9 +423,17 @@ async fn test_e2e_batch_settlement() {
         let request = QueryRequestPayload {
             hash,
             query: None,
-            payment,
+            payment: Some(payment),
             version_spec: None,
             payment_nonce: nonce,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
         };
         alice
             .ops
@@ -425,9 +454,17 @@ async fn test_e2e_batch_settlement() {
         let request = QueryRequestPayload {
             hash,
             query: None,
-            payment,
+            payment: Some(payment),
             version_spec: None,
             payment_nonce: nonce,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
         };
         alice
             .ops
@@ -503,9 +540,17 @@ async fn test_e2e_economics_tracking() {
     let request = QueryRequestPayload {
         hash,
         query: None,
-        payment,
+        payment: Some(payment),
         version_spec: None,
         payment_nonce: 1,
+        capability: None,
+        recipient_key: None,
+        challenge_response: None,
+        request_id: None,
+        section: None,
+        unit: None,
+        referrer: None,
+        entitlement: None,
     };
     alice
         .ops
@@ -524,7 +569,7 @@ async fn test_e2e_economics_tracking() {
 /// Test 5: Access control - private content cannot be queried
 #[tokio::test]
 async fn test_e2e_access_control() {
-    let mut alice = TestNode::new();
+    let alice = TestNode::new();
     let bob = TestNode::new();
 
     // Create private content (not published)
@@ -539,9 +584,17 @@ async fn test_e2e_access_control() {
     let request = QueryRequestPayload {
         hash,
         query: None,
-        payment,
+        payment: Some(payment),
         version_spec: None,
         payment_nonce: 1,
+        capability: None,
+        recipient_key: None,
+        challenge_response: None,
+        request_id: None,
+        section: None,
+        unit: None,
+        referrer: None,
+        entitlement: None,
     };
 
     let result = alice
@@ -560,7 +613,7 @@ async fn test_e2e_access_control() {
 /// Test 6: Insufficient payment is rejected
 #[tokio::test]
 async fn test_e2e_payment_validation() {
-    let mut alice = TestNode::new();
+    let alice = TestNode::new();
     let bob = TestNode::new();
 
     // Publish expensive content
@@ -587,9 +640,17 @@ async fn test_e2e_payment_validation() {
     let request = QueryRequestPayload {
         hash,
         query: None,
-        payment,
+        payment: Some(payment),
         version_spec: None,
         payment_nonce: 1,
+        capability: None,
+        recipient_key: None,
+        challenge_response: None,
+        request_id: None,
+        section: None,
+        unit: None,
+        referrer: None,
+        entitlement: None,
     };
 
     let result = alice
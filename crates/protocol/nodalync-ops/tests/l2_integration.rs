@@ -89,6 +89,7 @@ fn create_l1_from_l0(ops: &mut DefaultNodeOperations, l0_hash: &Hash) -> Result<
         )],
         derived_from: vec![*l0_hash],
         depth: 1,
+        attested: false,
     };
 
     // 6. Create L1 manifest with same owner as L0
@@ -478,6 +479,10 @@ fn test_l2_creator_economics_mixed_sources() {
         timestamp,
         channel_nonce: 0,
         distributor_signature: Signature::from_bytes([0u8; 64]),
+        app_fee: 0,
+        app_fee_recipient: None,
+        referral_fee: 0,
+        referrer: None,
     };
     let cached = CachedContent::new(
         l1_alice_hash,
@@ -644,7 +649,7 @@ async fn test_l2_cannot_publish() {
 #[test]
 fn test_only_l2_owner_can_derive_from_l2() {
     let (mut bob_ops, _temp_bob, _bob) = create_test_ops();
-    let (mut alice_ops, _temp_alice, _alice) = create_test_ops();
+    let (alice_ops, _temp_alice, _alice) = create_test_ops();
 
     // Bob creates L0 -> L1 -> L2
     let l0_hash = create_l0_content(&mut bob_ops, b"Bob data.", "Bob");
@@ -4,11 +4,17 @@
 //! and `MockSettlement` from `nodalync_test_utils`.
 
 use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
-use nodalync_ops::DefaultNodeOperations;
-use nodalync_store::{NodeStateConfig, QueuedDistribution, SettlementQueueStore};
+use nodalync_ops::{DefaultNodeOperations, SearchConfig, SearchSource};
+use nodalync_store::{
+    CacheStore, CachedContent, ManifestStore, NodeStateConfig, PeerInfo, PeerStore,
+    QueuedDistribution, SettlementQueueStore,
+};
 use nodalync_test_utils::*;
-use nodalync_types::{Metadata, Visibility};
+use nodalync_types::{ContentType, L1Summary, Metadata, Visibility};
+use nodalync_valid::validate_announcement;
+use nodalync_wire::{SearchFilters, SearchResponsePayload, SearchResult};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Helper to get the current timestamp.
 fn now() -> u64 {
@@ -21,7 +27,7 @@ fn now() -> u64 {
 
 #[tokio::test]
 async fn test_publish_and_query_local() {
-    let (mut ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+    let (ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
 
     // Create and publish free content
     let content = b"Knowledge about distributed systems and consensus algorithms";
@@ -40,7 +46,7 @@ async fn test_publish_and_query_local() {
 
 #[tokio::test]
 async fn test_publish_and_preview_local() {
-    let (mut ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+    let (ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
 
     let content = b"Content about cryptographic hash functions and their applications";
     let meta = Metadata::new("Crypto Hashes", content.len() as u64);
@@ -55,9 +61,30 @@ async fn test_publish_and_preview_local() {
     assert_eq!(preview.manifest.economics.price, 50);
 }
 
+#[tokio::test]
+async fn test_published_announcement_is_signed() {
+    let (mut ops, mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+    let (private_key, public_key) = generate_identity();
+    ops.set_private_key(private_key);
+
+    let content = b"Content announced with a publisher signature";
+    let meta = Metadata::new("Signed Announcement", content.len() as u64);
+    let hash = ops.create_content(content, meta).unwrap();
+    ops.publish_content(&hash, Visibility::Shared, 10)
+        .await
+        .unwrap();
+
+    let announcement = mock_net.dht_entries().remove(&hash).unwrap();
+    assert_eq!(announcement.publisher_key, Some(public_key));
+    assert_eq!(
+        validate_announcement(&announcement, 0).unwrap(),
+        Some(peer_id_from_public_key(&public_key))
+    );
+}
+
 #[tokio::test]
 async fn test_publish_multiple_and_search() {
-    let (mut ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+    let (ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
 
     // Create and publish multiple pieces of content
     let content1 = b"Machine learning algorithms for natural language processing";
@@ -87,6 +114,149 @@ async fn test_publish_multiple_and_search() {
         !results.is_empty(),
         "Search should find content matching 'learning'"
     );
+
+    // Price range narrows it to the vision content
+    let filters = SearchFilters {
+        min_price: Some(150),
+        max_price: Some(250),
+        ..Default::default()
+    };
+    let results = ops
+        .search_network_filtered("learning", &filters, 10)
+        .await
+        .unwrap();
+    let hashes: Vec<_> = results.iter().map(|r| r.hash).collect();
+    assert_eq!(hashes, vec![hash2]);
+    assert!(!hashes.contains(&hash1) && !hashes.contains(&hash3));
+}
+
+/// A SEARCH response with one result per (content, price).
+fn search_response(offers: &[(&[u8], u64)]) -> SearchResponsePayload {
+    let (_, _, owner) = test_keypair();
+    let results: Vec<SearchResult> = offers
+        .iter()
+        .map(|(content, price)| {
+            let hash = content_hash(content);
+            SearchResult {
+                hash,
+                content_type: ContentType::L0,
+                title: "Paper".to_string(),
+                owner,
+                l1_summary: L1Summary::empty(hash),
+                price: *price,
+                total_queries: 0,
+                relevance_score: 1.0,
+                publisher_addresses: vec![],
+            }
+        })
+        .collect();
+    SearchResponsePayload {
+        total_count: results.len() as u64,
+        results,
+    }
+}
+
+#[tokio::test]
+async fn test_federated_search_merges_peer_offers() {
+    let (mut ops, mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+    ops.config.search = SearchConfig::default().with_peer_timeout(200);
+
+    let pricey = nodalync_net::PeerId::random();
+    let cheap = nodalync_net::PeerId::random();
+    let slow = nodalync_net::PeerId::random();
+    let query = "paper".to_string();
+    let _mock_net = mock_net
+        .with_connected_peer(pricey)
+        .with_connected_peer(cheap)
+        .with_connected_peer(slow)
+        .with_peer_search_response(pricey, query.clone(), search_response(&[(b"paper", 300)]))
+        .with_peer_search_response(cheap, query.clone(), search_response(&[(b"paper", 100)]))
+        .with_peer_search_response(
+            slow,
+            query.clone(),
+            search_response(&[(b"paper", 1), (b"late paper", 1)]),
+        )
+        .with_peer_latency(slow, Duration::from_secs(5));
+
+    // The slow peer misses the deadline instead of holding up the search
+    let started = Instant::now();
+    let results = ops.search_network(&query, None, 10).await.unwrap();
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    // One result per hash, from the cheapest peer that answered in time
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].hash, content_hash(b"paper"));
+    assert_eq!(results[0].price, 100);
+    assert_eq!(results[0].source, SearchSource::Peer);
+    assert_eq!(results[0].publisher_peer_id, Some(cheap.to_string()));
+    assert!(results[0].latency_ms.unwrap() < 200);
+}
+
+#[tokio::test]
+async fn test_federated_search_prefers_reputable_peers() {
+    let (mut ops, mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+    ops.config.search = SearchConfig::default().with_max_peers(1);
+
+    let unknown = nodalync_net::PeerId::random();
+    let trusted = nodalync_net::PeerId::random();
+    let (_, trusted_key) = generate_identity();
+    let trusted_id = peer_id_from_public_key(&trusted_key);
+    ops.state
+        .peers
+        .upsert(&PeerInfo::new(trusted_id, trusted_key, vec![], now()).with_reputation(10))
+        .unwrap();
+
+    let query = "paper".to_string();
+    let _mock_net = mock_net
+        .with_connected_peer(unknown)
+        .with_connected_peer(trusted)
+        .with_peer_mapping(trusted, trusted_id)
+        .with_peer_search_response(unknown, query.clone(), search_response(&[(b"a", 1)]))
+        .with_peer_search_response(trusted, query.clone(), search_response(&[(b"b", 1)]));
+
+    // Only the top peer is asked, and reputation outranks connection order
+    let results = ops.search_network(&query, None, 10).await.unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].hash, content_hash(b"b"));
+    assert_eq!(results[0].publisher_peer_id, Some(trusted.to_string()));
+}
+
+#[tokio::test]
+async fn test_peer_search_result_verified_publisher() {
+    let (ops, mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+
+    let owner = nodalync_net::PeerId::random();
+    let relay = nodalync_net::PeerId::random();
+    let (_, owner_key) = generate_identity();
+    let owner_id = peer_id_from_public_key(&owner_key);
+
+    // Both peers return content owned by `owner_id`
+    let mut own_response = search_response(&[(b"own paper", 1)]);
+    own_response.results[0].owner = owner_id;
+    let mut relayed_response = search_response(&[(b"relayed paper", 1)]);
+    relayed_response.results[0].owner = owner_id;
+
+    let query = "paper".to_string();
+    let _mock_net = mock_net
+        .with_connected_peer(owner)
+        .with_connected_peer(relay)
+        .with_peer_mapping(owner, owner_id)
+        .with_peer_search_response(owner, query.clone(), own_response)
+        .with_peer_search_response(relay, query.clone(), relayed_response);
+
+    // Only the owner's own answer verifies the publisher
+    let results = ops.search_network(&query, None, 10).await.unwrap();
+    assert_eq!(results.len(), 2);
+    let own = results
+        .iter()
+        .find(|r| r.hash == content_hash(b"own paper"))
+        .unwrap();
+    assert!(own.verified_publisher);
+    let relayed = results
+        .iter()
+        .find(|r| r.hash == content_hash(b"relayed paper"))
+        .unwrap();
+    assert!(!relayed.verified_publisher);
 }
 
 // =========================================================================
@@ -95,7 +265,7 @@ async fn test_publish_multiple_and_search() {
 
 #[tokio::test]
 async fn test_full_settlement_loop_with_mocks() {
-    let (mut ops, _mock_net, mock_settle, _temp) = create_test_ops_with_mocks();
+    let (ops, _mock_net, mock_settle, _temp) = create_test_ops_with_mocks();
 
     let (_, _, peer1) = test_keypair();
     let (_, _, peer2) = test_keypair();
@@ -138,7 +308,7 @@ async fn test_full_settlement_loop_with_mocks() {
 #[tokio::test]
 async fn test_settlement_failure_does_not_clear_queue() {
     let mock_settle = MockSettlement::new().with_failure();
-    let (mut ops, _temp) = create_test_ops_with_settlement(Arc::new(mock_settle.clone()));
+    let (ops, _temp) = create_test_ops_with_settlement(Arc::new(mock_settle.clone()));
 
     let (_, _, peer) = test_keypair();
     let dist = QueuedDistribution::new(
@@ -176,7 +346,7 @@ async fn test_settlement_failure_does_not_clear_queue() {
 
 #[tokio::test]
 async fn test_multiple_settlement_rounds() {
-    let (mut ops, _mock_net, mock_settle, _temp) = create_test_ops_with_mocks();
+    let (ops, _mock_net, mock_settle, _temp) = create_test_ops_with_mocks();
 
     // Round 1
     let (_, _, peer1) = test_keypair();
@@ -217,7 +387,7 @@ async fn test_multiple_settlement_rounds() {
 #[tokio::test]
 async fn test_channel_open_without_network() {
     let mock_settle = MockSettlement::new().with_balance(100_0000_0000);
-    let (mut ops, _temp) = create_test_ops_with_settlement(Arc::new(mock_settle.clone()));
+    let (ops, _temp) = create_test_ops_with_settlement(Arc::new(mock_settle.clone()));
 
     let (_, _, peer) = test_keypair();
 
@@ -234,7 +404,7 @@ async fn test_channel_open_without_network() {
 
 #[tokio::test]
 async fn test_channel_lifecycle_with_mocks() {
-    let (mut ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+    let (ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
 
     let (_, _, peer) = test_keypair();
     let channel_id = content_hash(b"lifecycle-channel");
@@ -259,7 +429,7 @@ async fn test_channel_lifecycle_with_mocks() {
 
 #[tokio::test]
 async fn test_channel_accept_duplicate_fails() {
-    let (mut ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+    let (ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
 
     let (_, _, peer) = test_keypair();
     let channel_id = content_hash(b"dup-channel");
@@ -279,7 +449,7 @@ async fn test_channel_accept_duplicate_fails() {
 
 #[tokio::test]
 async fn test_channel_update_receive_payment() {
-    let (mut ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+    let (ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
 
     let (_, _, peer) = test_keypair();
     let channel_id = content_hash(b"receive-channel");
@@ -313,7 +483,7 @@ async fn test_channel_update_receive_payment() {
 
 #[tokio::test]
 async fn test_create_update_and_versions() {
-    let (mut ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+    let (ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
 
     // Create content
     let content1 = b"Version 1 of the document";
@@ -334,9 +504,84 @@ async fn test_create_update_and_versions() {
     assert!(ops.get_content_manifest(&hash2).unwrap().is_some());
 }
 
+#[tokio::test]
+async fn test_fetch_latest_version_rejects_bad_delta() {
+    let (owner, _owner_net, _owner_settle, _owner_temp) = create_test_ops_with_mocks();
+    let (mut ops, mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+
+    // Owner publishes two free versions
+    let content1 = b"Version 1 of the document. ".repeat(10);
+    let meta1 = Metadata::new("Doc", content1.len() as u64);
+    let hash1 = owner
+        .create_content_with_timestamp(&content1, meta1, now())
+        .unwrap();
+    let mut content2 = content1.clone();
+    content2.extend_from_slice(b"Version 2 adds a line.");
+    let meta2 = Metadata::new("Doc", content2.len() as u64);
+    let hash2 = owner
+        .update_content_with_timestamp(&hash1, &content2, meta2, now() + 1)
+        .unwrap();
+    owner
+        .publish_content(&hash2, Visibility::Shared, 0)
+        .await
+        .unwrap();
+
+    // Consumer holds version 1
+    let manifest1 = owner.get_content_manifest(&hash1).unwrap().unwrap();
+    ops.state_mut().manifests.store(&manifest1).unwrap();
+    let receipt = nodalync_wire::PaymentReceipt {
+        payment_id: hash1,
+        amount: 0,
+        timestamp: now(),
+        channel_nonce: 0,
+        distributor_signature: nodalync_crypto::Signature::from_bytes([0u8; 64]),
+        app_fee: 0,
+        app_fee_recipient: None,
+        referral_fee: 0,
+        referrer: None,
+    };
+    ops.state_mut()
+        .cache
+        .cache(CachedContent::new(
+            hash1,
+            content1.clone(),
+            owner.peer_id(),
+            now(),
+            receipt,
+        ))
+        .unwrap();
+
+    // Owner's reply, with the delta swapped for one that yields other bytes
+    let request = nodalync_wire::VersionRequestPayload {
+        version_root: hash1,
+        delta_from: Some(hash1),
+        include_manifests: false,
+    };
+    let mut response = owner
+        .handle_version_request(&ops.peer_id(), &request)
+        .unwrap();
+    let mut tampered = content2.clone();
+    tampered[0] = b'X';
+    response.delta.as_mut().unwrap().delta =
+        nodalync_store::delta::encode_delta(&content1, &tampered).unwrap();
+
+    let owner_libp2p = nodalync_net::PeerId::random();
+    let _ = mock_net
+        .clone()
+        .with_peer_mapping(owner_libp2p, owner.peer_id())
+        .with_version_response(hash1, response);
+
+    let result = ops.fetch_latest_version(&hash1).await;
+    assert!(matches!(
+        result,
+        Err(nodalync_ops::OpsError::ContentHashMismatch)
+    ));
+    assert!(!ops.is_content_cached(&hash2));
+}
+
 #[tokio::test]
 async fn test_derive_content_with_mocks() {
-    let (mut ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+    let (ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
 
     // Create two source documents
     let src1 = b"First source about consensus protocols";
@@ -366,7 +611,7 @@ async fn test_derive_content_with_mocks() {
 
 #[tokio::test]
 async fn test_paid_query_with_mock_settlement() {
-    let (mut ops, _mock_net, mock_settle, _temp) = create_test_ops_with_mocks();
+    let (ops, _mock_net, mock_settle, _temp) = create_test_ops_with_mocks();
 
     // Create and publish paid content
     let content = b"Premium content requiring payment";
@@ -397,9 +642,17 @@ async fn test_paid_query_with_mock_settlement() {
     let request = nodalync_wire::QueryRequestPayload {
         hash,
         query: None,
-        payment,
+        payment: Some(payment),
         version_spec: None,
         payment_nonce: 1,
+        capability: None,
+        recipient_key: None,
+        challenge_response: None,
+        request_id: None,
+        section: None,
+        unit: None,
+        referrer: None,
+        entitlement: None,
     };
 
     // With settlement configured, paid query should succeed
@@ -429,7 +682,7 @@ async fn test_paid_query_with_mock_settlement() {
 
 #[test]
 fn test_l1_extraction_with_mocks() {
-    let (mut ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+    let (ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
 
     let content = b"Apple and Microsoft announced partnerships. We found significant breakthroughs in quantum computing.";
     let meta = Metadata::new("Tech Announcements", content.len() as u64);
@@ -487,7 +740,7 @@ fn test_ops_with_network_only() {
 
 #[tokio::test]
 async fn test_preview_nonexistent_content() {
-    let (mut ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+    let (ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
 
     let unknown_hash = content_hash(b"this content does not exist");
     let result = ops.preview_content(&unknown_hash).await;
@@ -500,7 +753,7 @@ async fn test_preview_nonexistent_content() {
 
 #[tokio::test]
 async fn test_query_nonexistent_content() {
-    let (mut ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+    let (ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
 
     let unknown_hash = content_hash(b"missing content");
     let result = ops.query_content(&unknown_hash, 0, None).await;
@@ -509,7 +762,7 @@ async fn test_query_nonexistent_content() {
 
 #[tokio::test]
 async fn test_unpublish_makes_content_private() {
-    let (mut ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+    let (ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
 
     let content = b"Content that will be unpublished";
     let meta = Metadata::new("Temp Content", content.len() as u64);
@@ -528,7 +781,7 @@ async fn test_unpublish_makes_content_private() {
 
 #[tokio::test]
 async fn test_close_channel_no_channel() {
-    let (mut ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
+    let (ops, _mock_net, _mock_settle, _temp) = create_test_ops_with_mocks();
 
     let (private_key, _, peer) = test_keypair();
 
@@ -543,7 +796,7 @@ async fn test_close_channel_no_channel() {
 
 #[tokio::test]
 async fn test_force_settlement_empty_queue() {
-    let (mut ops, _mock_net, mock_settle, _temp) = create_test_ops_with_mocks();
+    let (ops, _mock_net, mock_settle, _temp) = create_test_ops_with_mocks();
 
     // Force settlement with empty queue
     let result = ops.force_settlement().await.unwrap();
@@ -555,7 +808,7 @@ async fn test_force_settlement_empty_queue() {
 
 #[tokio::test]
 async fn test_free_content_query_handler_no_settlement_needed() {
-    let (mut ops, _mock_net, mock_settle, _temp) = create_test_ops_with_mocks();
+    let (ops, _mock_net, mock_settle, _temp) = create_test_ops_with_mocks();
 
     // Create and publish free content
     let content = b"Free content for everyone";
@@ -581,9 +834,17 @@ async fn test_free_content_query_handler_no_settlement_needed() {
     let request = nodalync_wire::QueryRequestPayload {
         hash,
         query: None,
-        payment,
+        payment: Some(payment),
         version_spec: None,
         payment_nonce: 0,
+        capability: None,
+        recipient_key: None,
+        challenge_response: None,
+        request_id: None,
+        section: None,
+        unit: None,
+        referrer: None,
+        entitlement: None,
     };
 
     let result = ops.handle_query_request(&requester, &request).await;
@@ -204,7 +204,7 @@ async fn test_message_with_valid_signature_accepted() {
 /// under the soft-fail policy.
 #[tokio::test]
 async fn test_message_from_unknown_peer_still_processed() {
-    let (mut ops, node_private_key, _node_pubkey, _node_peer_id, _temp) = create_test_ops();
+    let (ops, node_private_key, _node_pubkey, _node_peer_id, _temp) = create_test_ops();
 
     // Create peer but do NOT register their key
     let (_, public_key) = generate_identity();
@@ -285,9 +285,17 @@ async fn test_forged_payment_signature_rejected() {
     let request = QueryRequestPayload {
         hash,
         query: None,
-        payment,
+        payment: Some(payment),
         version_spec: None,
         payment_nonce: 1,
+        capability: None,
+        recipient_key: None,
+        challenge_response: None,
+        request_id: None,
+        section: None,
+        unit: None,
+        referrer: None,
+        entitlement: None,
     };
 
     let result = ops.handle_query_request(&requester, &request).await;
@@ -339,9 +347,17 @@ async fn test_valid_signed_payment_accepted() {
     let request = QueryRequestPayload {
         hash,
         query: None,
-        payment,
+        payment: Some(payment),
         version_spec: None,
         payment_nonce: 1,
+        capability: None,
+        recipient_key: None,
+        challenge_response: None,
+        request_id: None,
+        section: None,
+        unit: None,
+        referrer: None,
+        entitlement: None,
     };
 
     let result = ops.handle_query_request(&requester, &request).await;
@@ -415,7 +431,7 @@ async fn test_channel_close_forged_signature_rejected() {
 /// under the soft-fail policy.
 #[tokio::test]
 async fn test_channel_close_unknown_peer_soft_fails() {
-    let (mut ops, node_private_key, _node_pubkey, _node_peer_id, _temp) = create_test_ops();
+    let (ops, node_private_key, _node_pubkey, _node_peer_id, _temp) = create_test_ops();
 
     // Create peer but do NOT register their key
     let (_, pubkey) = generate_identity();
@@ -502,9 +518,17 @@ async fn test_payment_record_has_real_signature() {
     let request = QueryRequestPayload {
         hash,
         query: None,
-        payment,
+        payment: Some(payment),
         version_spec: None,
         payment_nonce: 1,
+        capability: None,
+        recipient_key: None,
+        challenge_response: None,
+        request_id: None,
+        section: None,
+        unit: None,
+        referrer: None,
+        entitlement: None,
     };
 
     let result = ops.handle_query_request(&requester, &request).await;
@@ -569,9 +593,17 @@ async fn test_receipt_has_real_signature() {
     let request = QueryRequestPayload {
         hash,
         query: None,
-        payment,
+        payment: Some(payment),
         version_spec: None,
         payment_nonce: 1,
+        capability: None,
+        recipient_key: None,
+        challenge_response: None,
+        request_id: None,
+        section: None,
+        unit: None,
+        referrer: None,
+        entitlement: None,
     };
 
     let result = ops.handle_query_request(&requester, &request).await;
@@ -614,7 +646,7 @@ fn test_lock_poisoning_returns_error_not_panic() {
     let _ = handle.join();
 
     // Now the mutex is poisoned. Create a store and try operations.
-    let mut peer_store = nodalync_store::SqlitePeerStore::new(conn.clone());
+    let peer_store = nodalync_store::SqlitePeerStore::new(conn.clone());
 
     let (_, public_key) = generate_identity();
     let peer_id = peer_id_from_public_key(&public_key);
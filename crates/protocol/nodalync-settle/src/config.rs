@@ -153,6 +153,13 @@ pub struct GasConfig {
     pub max_gas_dispute: u64,
     /// Max gas for withdraw operations
     pub max_gas_withdraw: u64,
+    /// Max gas for posting and slashing publisher bonds
+    #[serde(default = "default_max_gas_bond")]
+    pub max_gas_bond: u64,
+}
+
+fn default_max_gas_bond() -> u64 {
+    100_000
 }
 
 impl Default for GasConfig {
@@ -165,6 +172,7 @@ impl Default for GasConfig {
             max_gas_channel_close: 200_000,
             max_gas_dispute: 300_000,
             max_gas_withdraw: 100_000,
+            max_gas_bond: default_max_gas_bond(),
         }
     }
 }
@@ -296,5 +304,6 @@ mod tests {
         assert_eq!(gas.max_gas_channel_close, 200_000);
         assert_eq!(gas.max_gas_dispute, 300_000);
         assert_eq!(gas.max_gas_withdraw, 100_000);
+        assert_eq!(gas.max_gas_bond, 100_000);
     }
 }
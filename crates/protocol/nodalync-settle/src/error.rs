@@ -1,5 +1,6 @@
 //! Error types for the settlement module.
 
+use nodalync_types::ErrorCode;
 use thiserror::Error;
 
 /// Result type alias for settlement operations.
@@ -141,6 +142,37 @@ impl SettleError {
     pub fn is_retryable(&self) -> bool {
         matches!(self, Self::Network(_) | Self::Timeout(_))
     }
+
+    /// Get the protocol error code for this error.
+    ///
+    /// Retryable errors map to retryable codes, so
+    /// [`ErrorCode::is_retryable`] agrees with [`Self::is_retryable`].
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            Self::InsufficientBalance { .. } => ErrorCode::InsufficientBalance,
+            Self::AccountNotFound(_) => ErrorCode::PeerNotFound,
+            Self::ChannelNotFound(_) | Self::ChannelAlreadyExists(_) => ErrorCode::ChannelNotFound,
+            Self::ChannelNotOpen(_) | Self::DisputePeriodNotElapsed => ErrorCode::ChannelClosed,
+            Self::InvalidNonce { .. } => ErrorCode::InvalidNonce,
+            Self::Network(_) => ErrorCode::ConnectionFailed,
+            Self::Timeout(_) => ErrorCode::Timeout,
+            Self::TransactionFailed(_)
+            | Self::EmptyBatch
+            | Self::NoHederaAccount
+            | Self::HederaSdk(_)
+            | Self::Config(_)
+            | Self::InvalidAccountId(_)
+            | Self::InvalidTransactionId(_)
+            | Self::Internal(_)
+            | Self::Io(_) => ErrorCode::InternalError,
+        }
+    }
+}
+
+impl From<&SettleError> for ErrorCode {
+    fn from(error: &SettleError) -> Self {
+        error.error_code()
+    }
 }
 
 /// Classify an SDK error into the appropriate `SettleError` variant.
@@ -195,6 +227,24 @@ mod tests {
         assert!(!SettleError::NoHederaAccount.is_retryable());
     }
 
+    #[test]
+    fn test_error_code_matches_retryable() {
+        let errors = [
+            SettleError::network("connection refused"),
+            SettleError::timeout("operation timed out"),
+            SettleError::EmptyBatch,
+            SettleError::hedera_sdk("INVALID_SIGNATURE"),
+            SettleError::insufficient_balance(100, 200),
+        ];
+        for err in &errors {
+            assert_eq!(err.error_code().is_retryable(), err.is_retryable());
+        }
+        assert_eq!(
+            ErrorCode::from(&SettleError::insufficient_balance(100, 200)),
+            ErrorCode::InsufficientBalance
+        );
+    }
+
     #[test]
     fn test_error_display() {
         let err = SettleError::channel_not_found("ch123");
@@ -9,11 +9,11 @@ use std::sync::RwLock;
 use async_trait::async_trait;
 use hiero_sdk::{
     AccountBalanceQuery, AccountId as HederaAccountId, Client, ContractCallQuery,
-    ContractExecuteTransaction, ContractFunctionParameters, ContractId, Hbar, PrivateKey,
-    TransactionId as HederaTransactionId, TransactionReceiptQuery,
+    ContractExecuteTransaction, ContractFunctionParameters, ContractFunctionResult, ContractId,
+    Hbar, PrivateKey, TransactionId as HederaTransactionId, TransactionReceiptQuery,
 };
 use nodalync_crypto::{Hash, PeerId, Signature, Timestamp};
-use nodalync_types::SettlementBatch;
+use nodalync_types::{PendingDispute, SettlementBatch};
 use nodalync_wire::{ChannelBalances, ChannelUpdatePayload};
 use tracing::{debug, info, warn};
 
@@ -22,7 +22,9 @@ use crate::config::HederaConfig;
 use crate::error::{SettleError, SettleResult};
 use crate::retry::RetryPolicy;
 use crate::traits::Settlement;
-use crate::types::{AccountId, Attestation, ChannelId, SettlementStatus, TransactionId};
+use crate::types::{
+    AccountId, Attestation, ChannelId, OnChainDispute, SettlementStatus, TransactionId,
+};
 
 /// Hedera settlement implementation.
 ///
@@ -267,6 +269,37 @@ impl HederaSettlement {
         debug!(account = %hedera_account, evm_address = %evm_address, "Resolved EVM address");
         Ok(evm_address)
     }
+
+    /// Call a contract view function taking a channel ID.
+    async fn call_channel_view(
+        &self,
+        function: &str,
+        channel_id: &ChannelId,
+    ) -> SettleResult<ContractFunctionResult> {
+        self.retry_policy
+            .execute(|| async {
+                ContractCallQuery::new()
+                    .contract_id(self.contract_id)
+                    .gas(100_000)
+                    .function_with_parameters(
+                        function,
+                        ContractFunctionParameters::new().add_bytes32(&channel_id.0 .0),
+                    )
+                    .execute(&self.client)
+                    .await
+                    .map_err(crate::error::classify_sdk_error)
+            })
+            .await
+    }
+}
+
+/// Decode the `index`th return value of a contract call as a `u64`.
+fn decode_u64(result: &ContractFunctionResult, index: usize, what: &str) -> SettleResult<u64> {
+    result
+        .get_u256(index)
+        .ok_or_else(|| SettleError::hedera_sdk(format!("failed to decode {} from contract", what)))?
+        .try_into()
+        .map_err(|_| SettleError::hedera_sdk(format!("{} overflow", what)))
 }
 
 #[async_trait]
@@ -501,6 +534,146 @@ impl Settlement for HederaSettlement {
         Ok(None)
     }
 
+    async fn post_bond(&self, amount: u64) -> SettleResult<TransactionId> {
+        debug!(amount, "Posting publisher bond");
+
+        let tx = self
+            .retry_policy
+            .execute(|| async {
+                ContractExecuteTransaction::new()
+                    .contract_id(self.contract_id)
+                    .gas(self.config.gas.max_gas_bond)
+                    .function_with_parameters(
+                        "postBond",
+                        ContractFunctionParameters::new().add_uint256(amount.into()),
+                    )
+                    .execute(&self.client)
+                    .await
+                    .map_err(crate::error::classify_sdk_error)
+            })
+            .await?;
+
+        let receipt = self.wait_for_receipt(&tx.transaction_id).await?;
+
+        if receipt.status != hiero_sdk::Status::Success {
+            return Err(SettleError::transaction_failed(format!(
+                "post bond failed: {:?}",
+                receipt.status
+            )));
+        }
+
+        info!(amount, tx_id = %tx.transaction_id, "Bond posted");
+        Ok(Self::from_hedera_tx_id(&tx.transaction_id))
+    }
+
+    async fn get_bond(&self, account: &AccountId) -> SettleResult<u64> {
+        let evm_address = self.resolve_evm_address(account).await?;
+
+        let result = self
+            .retry_policy
+            .execute(|| async {
+                ContractCallQuery::new()
+                    .contract_id(self.contract_id)
+                    .gas(100_000)
+                    .function_with_parameters(
+                        "bonds",
+                        ContractFunctionParameters::new().add_address(&evm_address),
+                    )
+                    .execute(&self.client)
+                    .await
+                    .map_err(crate::error::classify_sdk_error)
+            })
+            .await?;
+
+        let bond = result
+            .get_u256(0)
+            .ok_or_else(|| SettleError::hedera_sdk("failed to decode bond from contract"))?
+            .try_into()
+            .map_err(|_| SettleError::hedera_sdk("bond overflow"))?;
+
+        Ok(bond)
+    }
+
+    async fn slash_bond(
+        &self,
+        account: &AccountId,
+        amount: u64,
+        evidence: &Hash,
+    ) -> SettleResult<TransactionId> {
+        debug!(account = %account, amount, evidence = %evidence, "Slashing bond");
+
+        let evm_address = self.resolve_evm_address(account).await?;
+        let tx = self
+            .retry_policy
+            .execute(|| async {
+                ContractExecuteTransaction::new()
+                    .contract_id(self.contract_id)
+                    .gas(self.config.gas.max_gas_bond)
+                    .function_with_parameters(
+                        "slashBond",
+                        ContractFunctionParameters::new()
+                            .add_address(&evm_address)
+                            .add_uint256(amount.into())
+                            .add_bytes32(&evidence.0),
+                    )
+                    .execute(&self.client)
+                    .await
+                    .map_err(crate::error::classify_sdk_error)
+            })
+            .await?;
+
+        let receipt = self.wait_for_receipt(&tx.transaction_id).await?;
+
+        if receipt.status != hiero_sdk::Status::Success {
+            return Err(SettleError::transaction_failed(format!(
+                "slash bond failed: {:?}",
+                receipt.status
+            )));
+        }
+
+        info!(account = %account, amount, tx_id = %tx.transaction_id, "Bond slashed");
+        Ok(Self::from_hedera_tx_id(&tx.transaction_id))
+    }
+
+    async fn submit_fraud_proof(
+        &self,
+        provider: &AccountId,
+        proof_hash: &Hash,
+    ) -> SettleResult<TransactionId> {
+        debug!(provider = %provider, proof = %proof_hash, "Submitting fraud proof");
+
+        let evm_address = self.resolve_evm_address(provider).await?;
+        let tx = self
+            .retry_policy
+            .execute(|| async {
+                ContractExecuteTransaction::new()
+                    .contract_id(self.contract_id)
+                    .gas(self.config.gas.max_gas_bond)
+                    .function_with_parameters(
+                        "reportFraud",
+                        ContractFunctionParameters::new()
+                            .add_address(&evm_address)
+                            .add_bytes32(&proof_hash.0),
+                    )
+                    .execute(&self.client)
+                    .await
+                    .map_err(crate::error::classify_sdk_error)
+            })
+            .await?;
+
+        let receipt = self.wait_for_receipt(&tx.transaction_id).await?;
+
+        if receipt.status != hiero_sdk::Status::Success {
+            return Err(SettleError::transaction_failed(format!(
+                "fraud proof submission failed: {:?}",
+                receipt.status
+            )));
+        }
+
+        info!(provider = %provider, tx_id = %tx.transaction_id, "Fraud proof recorded");
+        Ok(Self::from_hedera_tx_id(&tx.transaction_id))
+    }
+
     async fn open_channel(
         &self,
         channel_id: &ChannelId,
@@ -728,6 +901,29 @@ impl Settlement for HederaSettlement {
         Ok(Self::from_hedera_tx_id(&tx.transaction_id))
     }
 
+    async fn get_dispute(&self, channel_id: &ChannelId) -> SettleResult<Option<OnChainDispute>> {
+        // ChannelStatus: NonExistent, Open, Disputed, Closed
+        let channel = self.call_channel_view("getChannel", channel_id).await?;
+        if decode_u64(&channel, 5, "channel status")? != 2 {
+            return Ok(None);
+        }
+
+        let details = self
+            .call_channel_view("getDisputeDetails", channel_id)
+            .await?;
+        // Block timestamps are in seconds
+        let dispute_start = decode_u64(&details, 0, "dispute start")?.saturating_mul(1000);
+
+        Ok(Some(OnChainDispute {
+            channel_id: channel_id.clone(),
+            dispute_start,
+            resolvable_at: dispute_start.saturating_add(PendingDispute::DISPUTE_PERIOD_MS),
+            nonce: decode_u64(&details, 1, "disputed nonce")?,
+            balance1: decode_u64(&details, 2, "disputed balance")?,
+            balance2: decode_u64(&details, 3, "disputed balance")?,
+        }))
+    }
+
     async fn settle_batch(&self, batch: &SettlementBatch) -> SettleResult<TransactionId> {
         if batch.is_empty() {
             return Err(SettleError::EmptyBatch);
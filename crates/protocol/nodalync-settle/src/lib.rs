@@ -9,6 +9,7 @@
 //! The settlement module handles:
 //! - **Deposits/Withdrawals**: Managing tokens in the settlement contract
 //! - **Content Attestation**: Creating on-chain proofs of content ownership
+//! - **Publisher Bonds**: Posting and slashing publishers' stakes
 //! - **Payment Channels**: Opening, updating, and closing payment channels
 //! - **Batch Settlement**: Distributing payments to ALL recipients in a batch
 //!
@@ -61,6 +62,7 @@
 //! Key methods:
 //! - `deposit()` / `withdraw()` / `get_balance()` - Balance management
 //! - `attest()` / `get_attestation()` - Content attestation
+//! - `post_bond()` / `get_bond()` / `slash_bond()` - Publisher bonds
 //! - `open_channel()` / `close_channel()` - Payment channel lifecycle
 //! - `settle_batch()` - Core batch settlement operation
 //!
@@ -91,7 +93,9 @@ pub use retry::RetryPolicy;
 pub use traits::Settlement;
 
 // Re-export key types from types module
-pub use types::{AccountId, Attestation, ChannelId, SettlementStatus, TransactionId};
+pub use types::{
+    AccountId, Attestation, ChannelId, OnChainDispute, SettlementStatus, TransactionId,
+};
 
 #[cfg(test)]
 mod tests {
@@ -6,7 +6,9 @@ use nodalync_types::SettlementBatch;
 use nodalync_wire::{ChannelBalances, ChannelUpdatePayload};
 
 use crate::error::SettleResult;
-use crate::types::{AccountId, Attestation, ChannelId, SettlementStatus, TransactionId};
+use crate::types::{
+    AccountId, Attestation, ChannelId, OnChainDispute, SettlementStatus, TransactionId,
+};
 
 /// Trait for on-chain settlement operations.
 ///
@@ -60,6 +62,42 @@ pub trait Settlement: Send + Sync {
     /// Returns `None` if no attestation exists.
     async fn get_attestation(&self, content_hash: &Hash) -> SettleResult<Option<Attestation>>;
 
+    // =========================================================================
+    // Publisher Bonds
+    // =========================================================================
+
+    /// Bond tokens from the settlement contract balance.
+    ///
+    /// Moves `amount` from our contract balance into our publisher bond.
+    /// Bonds accumulate and can't be withdrawn while they back content.
+    async fn post_bond(&self, amount: u64) -> SettleResult<TransactionId>;
+
+    /// Get the total bond posted by an account.
+    ///
+    /// Returns 0 if the account has never posted a bond.
+    async fn get_bond(&self, account: &AccountId) -> SettleResult<u64>;
+
+    /// Slash up to `amount` from an account's bond on proven fraud.
+    ///
+    /// `evidence` identifies the proof, e.g. the hash of the bad content
+    /// the account's owner served.
+    async fn slash_bond(
+        &self,
+        account: &AccountId,
+        amount: u64,
+        evidence: &Hash,
+    ) -> SettleResult<TransactionId>;
+
+    /// Record a fraud proof against an account on-chain.
+    ///
+    /// `proof_hash` is the hash of the proof, which is distributed
+    /// off-chain. Anyone can record a proof; each is recorded once.
+    async fn submit_fraud_proof(
+        &self,
+        provider: &AccountId,
+        proof_hash: &Hash,
+    ) -> SettleResult<TransactionId>;
+
     // =========================================================================
     // Payment Channels
     // =========================================================================
@@ -109,6 +147,12 @@ pub trait Settlement: Send + Sync {
     /// Settles the channel using the highest nonce state submitted.
     async fn resolve_dispute(&self, channel_id: &ChannelId) -> SettleResult<TransactionId>;
 
+    /// Get the dispute open on a channel.
+    ///
+    /// Returns `None` if the channel is not in its dispute period, or is
+    /// unknown.
+    async fn get_dispute(&self, channel_id: &ChannelId) -> SettleResult<Option<OnChainDispute>>;
+
     // =========================================================================
     // Batch Settlement
     // =========================================================================
@@ -244,6 +244,24 @@ impl OnChainChannelStatus {
     }
 }
 
+/// A dispute open on a channel on-chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnChainDispute {
+    /// Channel identifier
+    pub channel_id: ChannelId,
+    /// When the dispute was initiated (ms)
+    pub dispute_start: Timestamp,
+    /// When the dispute can be resolved; counter-disputes must be submitted
+    /// before then (ms)
+    pub resolvable_at: Timestamp,
+    /// Nonce of the highest state submitted so far
+    pub nonce: u64,
+    /// Balance of participant 1 in that state
+    pub balance1: u64,
+    /// Balance of participant 2 in that state
+    pub balance2: u64,
+}
+
 /// Mapping entry for PeerId to AccountId.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AccountMapping {
@@ -9,6 +9,7 @@ use nodalync_crypto::{content_hash, PeerId, Signature};
 use nodalync_settle::{AccountId, Settlement};
 use nodalync_test_utils::MockSettlement;
 use nodalync_types::settlement::{SettlementBatch, SettlementEntry};
+use nodalync_types::PendingDispute;
 use nodalync_wire::{ChannelBalances, ChannelUpdatePayload};
 
 /// Helper to create a dummy 64-byte signature for testing.
@@ -215,6 +216,12 @@ async fn test_dispute_counter_dispute_resolve() {
     assert!(!tx.as_str().is_empty());
     // Channel is still tracked (dispute does not remove it)
     assert_eq!(settle.channel_count(), 1);
+    let dispute = settle.get_dispute(&channel_id).await.unwrap().unwrap();
+    assert_eq!(dispute.nonce, 5);
+    assert_eq!(
+        dispute.resolvable_at - dispute.dispute_start,
+        PendingDispute::DISPUTE_PERIOD_MS
+    );
 
     // Submit a counter-dispute with higher nonce
     let better_state = ChannelUpdatePayload {
@@ -230,11 +237,21 @@ async fn test_dispute_counter_dispute_resolve() {
         .unwrap();
     assert!(!tx2.as_str().is_empty());
     assert_eq!(settle.channel_count(), 1);
+    let dispute = settle.get_dispute(&channel_id).await.unwrap().unwrap();
+    assert_eq!(dispute.nonce, 10);
+    assert_eq!((dispute.balance1, dispute.balance2), (10_000, 10_000));
+
+    // A counter-dispute must raise the nonce
+    assert!(settle
+        .counter_dispute(&channel_id, &claimed_state)
+        .await
+        .is_err());
 
     // Resolve the dispute (removes the channel in mock)
     let tx3 = settle.resolve_dispute(&channel_id).await.unwrap();
     assert!(!tx3.as_str().is_empty());
     assert_eq!(settle.channel_count(), 0);
+    assert!(settle.get_dispute(&channel_id).await.unwrap().is_none());
 }
 
 // =============================================================================
@@ -0,0 +1,105 @@
+//! Manifest store and announcement search benchmarks.
+//!
+//! Stores are pre-populated so that each operation runs against a
+//! realistically sized table. Run with `cargo bench -p nodalync-store`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use nodalync_crypto::{content_hash, PeerId};
+use nodalync_store::{ManifestFilter, ManifestStore, NodeState, NodeStateConfig};
+use nodalync_types::{ContentType, L1Summary, Manifest, Metadata};
+use nodalync_wire::AnnouncePayload;
+use tempfile::TempDir;
+
+/// Manifests in the store before measuring.
+const MANIFEST_ROWS: u64 = 100_000;
+
+/// Announcements in the store before measuring.
+const ANNOUNCEMENT_ROWS: u64 = 10_000;
+
+/// Number of distinct owners across the manifests.
+const OWNERS: u64 = 100;
+
+fn owner(i: u64) -> PeerId {
+    let mut id = [0u8; 20];
+    id[..8].copy_from_slice(&(i % OWNERS).to_be_bytes());
+    PeerId(id)
+}
+
+fn manifest(i: u64) -> Manifest {
+    let hash = content_hash(&i.to_be_bytes());
+    let metadata = Metadata::new(format!("Document {} about topic {}", i, i % 1000), 1024);
+    Manifest::new_l0(hash, owner(i), metadata, 1_700_000_000_000 + i)
+}
+
+fn populated_state() -> (TempDir, NodeState) {
+    let dir = TempDir::new().unwrap();
+    let state = NodeState::open(NodeStateConfig::new(dir.path())).unwrap();
+    for i in 0..MANIFEST_ROWS {
+        state.manifests.store(&manifest(i)).unwrap();
+    }
+    for i in 0..ANNOUNCEMENT_ROWS {
+        let hash = content_hash(format!("announcement {}", i).as_bytes());
+        state.store_announcement(AnnouncePayload {
+            hash,
+            content_type: ContentType::L0,
+            title: format!("Announced paper {} on subject {}", i, i % 500),
+            l1_summary: L1Summary::empty(hash),
+            price: 100,
+            addresses: vec!["/ip4/10.0.0.1/tcp/9000".to_string()],
+            publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
+        });
+    }
+    (dir, state)
+}
+
+fn bench_manifest_store(c: &mut Criterion) {
+    let (_dir, state) = populated_state();
+    let mut group = c.benchmark_group("manifest_store_100k");
+
+    let mut next = MANIFEST_ROWS;
+    group.bench_function("insert", |b| {
+        b.iter(|| {
+            state.manifests.store(&manifest(next)).unwrap();
+            next += 1;
+        })
+    });
+    group.bench_function("load_indexed", |b| {
+        let hash = manifest(42).hash;
+        b.iter(|| state.manifests.load(black_box(&hash)).unwrap())
+    });
+    group.bench_function("list_first_page", |b| {
+        b.iter(|| {
+            state
+                .manifests
+                .list(black_box(ManifestFilter::new().limit(50)))
+        })
+    });
+    group.bench_function("list_deep_page", |b| {
+        let filter = ManifestFilter::new().offset(90_000).limit(50);
+        b.iter(|| state.manifests.list(black_box(filter.clone())))
+    });
+    group.bench_function("list_by_owner", |b| {
+        let filter = ManifestFilter::new().with_owner(owner(42)).limit(50);
+        b.iter(|| state.manifests.list(black_box(filter.clone())))
+    });
+    group.bench_function("list_text_query", |b| {
+        let filter = ManifestFilter::new().with_text_query("topic 421").limit(50);
+        b.iter(|| state.manifests.list(black_box(filter.clone())))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("announcements_10k");
+    group.bench_function("search", |b| {
+        b.iter(|| state.search_announcements(black_box("subject 42"), None, 20))
+    });
+    group.bench_function("search_no_match", |b| {
+        b.iter(|| state.search_announcements(black_box("nonexistent"), None, 20))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_manifest_store);
+criterion_main!(benches);
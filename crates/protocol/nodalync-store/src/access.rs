@@ -0,0 +1,420 @@
+//! Content access log storage.
+//!
+//! This module records previews and paid queries served for local content,
+//! and anonymized usage reports from consumers, which publishers aggregate
+//! into per-content analytics.
+
+use rusqlite::{params, Connection};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{Hash, PeerId, Timestamp};
+use nodalync_types::Amount;
+
+use crate::error::{Result, StoreError};
+use crate::traits::AccessLogStore;
+use crate::types::{AccessKind, AccessRecord, AccessRequester, UsageRecord};
+
+/// SQLite-based content access log.
+pub struct SqliteAccessLog {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteAccessLog {
+    /// Create a new access log with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+}
+
+impl AccessLogStore for SqliteAccessLog {
+    fn record(&self, record: &AccessRecord) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let (requester_bytes, requester_hashed) = match &record.requester {
+            AccessRequester::Peer(peer) => (peer.0.to_vec(), false),
+            AccessRequester::Hashed(hash) => (hash.0.to_vec(), true),
+        };
+
+        conn.prepare_cached(
+            "INSERT INTO content_access (content_hash, requester, requester_hashed, kind, amount, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?
+        .execute(params![
+            record.content_hash.0.to_vec(),
+            requester_bytes,
+            requester_hashed,
+            record.kind.as_i64(),
+            record.amount as i64,
+            record.timestamp as i64,
+        ])?;
+
+        Ok(())
+    }
+
+    fn for_content(&self, hash: &Hash) -> Result<Vec<AccessRecord>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT requester, requester_hashed, kind, amount, timestamp FROM content_access
+             WHERE content_hash = ?1 ORDER BY timestamp ASC, id ASC",
+        )?;
+
+        let records: Vec<AccessRecord> = stmt
+            .query_map([hash.0.to_vec()], |row| {
+                let requester: Vec<u8> = row.get(0)?;
+                let requester_hashed: bool = row.get(1)?;
+                let kind: i64 = row.get(2)?;
+                let amount: i64 = row.get(3)?;
+                let timestamp: i64 = row.get(4)?;
+                Ok((requester, requester_hashed, kind, amount, timestamp))
+            })?
+            .filter_map(|r| r.ok())
+            .filter_map(|(requester, requester_hashed, kind, amount, timestamp)| {
+                AccessKind::from_i64(kind).map(|kind| AccessRecord {
+                    content_hash: *hash,
+                    requester: to_requester(&requester, requester_hashed),
+                    kind,
+                    amount: amount as Amount,
+                    timestamp: timestamp as Timestamp,
+                })
+            })
+            .collect();
+
+        Ok(records)
+    }
+
+    fn between(&self, from: Timestamp, to: Timestamp) -> Result<Vec<AccessRecord>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT content_hash, requester, requester_hashed, kind, amount, timestamp
+             FROM content_access
+             WHERE timestamp >= ?1 AND timestamp < ?2 ORDER BY timestamp ASC, id ASC",
+        )?;
+
+        let records: Vec<AccessRecord> = stmt
+            .query_map(params![from as i64, to as i64], |row| {
+                let content_hash: Vec<u8> = row.get(0)?;
+                let requester: Vec<u8> = row.get(1)?;
+                let requester_hashed: bool = row.get(2)?;
+                let kind: i64 = row.get(3)?;
+                let amount: i64 = row.get(4)?;
+                let timestamp: i64 = row.get(5)?;
+                Ok((
+                    content_hash,
+                    requester,
+                    requester_hashed,
+                    kind,
+                    amount,
+                    timestamp,
+                ))
+            })?
+            .filter_map(|r| r.ok())
+            .filter_map(
+                |(content_hash, requester, requester_hashed, kind, amount, timestamp)| {
+                    AccessKind::from_i64(kind).map(|kind| AccessRecord {
+                        content_hash: bytes_to_hash(&content_hash),
+                        requester: to_requester(&requester, requester_hashed),
+                        kind,
+                        amount: amount as Amount,
+                        timestamp: timestamp as Timestamp,
+                    })
+                },
+            )
+            .collect();
+
+        Ok(records)
+    }
+
+    fn first_seen_between(&self, from: Timestamp, to: Timestamp) -> Result<Vec<AccessRequester>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT requester, requester_hashed FROM content_access
+             GROUP BY requester, requester_hashed
+             HAVING MIN(timestamp) >= ?1 AND MIN(timestamp) < ?2
+             ORDER BY MIN(timestamp) ASC",
+        )?;
+
+        let requesters = stmt
+            .query_map(params![from as i64, to as i64], |row| {
+                let requester: Vec<u8> = row.get(0)?;
+                let requester_hashed: bool = row.get(1)?;
+                Ok(to_requester(&requester, requester_hashed))
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(requesters)
+    }
+
+    fn record_usage(&self, record: &UsageRecord) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        conn.execute(
+            "INSERT INTO usage_reports (content_hash, bytes_read, context_tokens, rating, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                record.content_hash.0.to_vec(),
+                record.bytes_read as i64,
+                record.context_tokens.map(i64::from),
+                record.rating.map(i64::from),
+                record.timestamp as i64,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn usage_for_content(&self, hash: &Hash) -> Result<Vec<UsageRecord>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT bytes_read, context_tokens, rating, timestamp FROM usage_reports
+             WHERE content_hash = ?1 ORDER BY timestamp ASC, id ASC",
+        )?;
+
+        let records: Vec<UsageRecord> = stmt
+            .query_map([hash.0.to_vec()], |row| {
+                let bytes_read: i64 = row.get(0)?;
+                let context_tokens: Option<i64> = row.get(1)?;
+                let rating: Option<i64> = row.get(2)?;
+                let timestamp: i64 = row.get(3)?;
+                Ok(UsageRecord {
+                    content_hash: *hash,
+                    bytes_read: bytes_read as u64,
+                    context_tokens: context_tokens.map(|t| t as u32),
+                    rating: rating.map(|r| r as u8),
+                    timestamp: timestamp as Timestamp,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(records)
+    }
+
+    fn prune(&self, older_than: Timestamp) -> Result<u64> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let deleted = conn.execute(
+            "DELETE FROM content_access WHERE timestamp < ?1",
+            [older_than as i64],
+        )?;
+        let deleted_usage = conn.execute(
+            "DELETE FROM usage_reports WHERE timestamp < ?1",
+            [older_than as i64],
+        )?;
+
+        Ok((deleted + deleted_usage) as u64)
+    }
+}
+
+/// Convert bytes to Hash.
+fn to_requester(bytes: &[u8], hashed: bool) -> AccessRequester {
+    if hashed {
+        AccessRequester::Hashed(bytes_to_hash(bytes))
+    } else {
+        AccessRequester::Peer(bytes_to_peer_id(bytes))
+    }
+}
+
+fn bytes_to_hash(bytes: &[u8]) -> Hash {
+    let mut arr = [0u8; 32];
+    if bytes.len() >= 32 {
+        arr.copy_from_slice(&bytes[..32]);
+    }
+    Hash(arr)
+}
+
+/// Convert bytes to PeerId.
+fn bytes_to_peer_id(bytes: &[u8]) -> PeerId {
+    let mut arr = [0u8; 20];
+    if bytes.len() >= 20 {
+        arr.copy_from_slice(&bytes[..20]);
+    }
+    PeerId::from_bytes(arr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+
+    fn setup_log() -> SqliteAccessLog {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteAccessLog::new(Arc::new(Mutex::new(conn)))
+    }
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    #[test]
+    fn test_record_and_list() {
+        let log = setup_log();
+        let hash = content_hash(b"content");
+        let other = content_hash(b"other");
+        let peer = test_peer_id();
+        let hashed = content_hash(b"hashed requester");
+
+        log.record(&AccessRecord::new(
+            hash,
+            AccessRequester::Peer(peer),
+            AccessKind::Query,
+            50,
+            2000,
+        ))
+        .unwrap();
+        log.record(&AccessRecord::new(
+            hash,
+            AccessRequester::Hashed(hashed),
+            AccessKind::Preview,
+            0,
+            1000,
+        ))
+        .unwrap();
+        log.record(&AccessRecord::new(
+            other,
+            AccessRequester::Peer(peer),
+            AccessKind::Preview,
+            0,
+            1500,
+        ))
+        .unwrap();
+
+        let records = log.for_content(&hash).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].requester, AccessRequester::Hashed(hashed));
+        assert_eq!(records[0].kind, AccessKind::Preview);
+        assert_eq!(records[1].requester, AccessRequester::Peer(peer));
+        assert_eq!(records[1].kind, AccessKind::Query);
+        assert_eq!(records[1].amount, 50);
+
+        assert!(log.for_content(&content_hash(b"none")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_between_and_first_seen() {
+        let log = setup_log();
+        let hash = content_hash(b"content");
+        let other = content_hash(b"other");
+        let old_peer = test_peer_id();
+        let new_peer = test_peer_id();
+
+        for (content, peer, timestamp) in [
+            (hash, old_peer, 500),
+            (other, old_peer, 1500),
+            (hash, new_peer, 1200),
+            (other, new_peer, 2500),
+        ] {
+            log.record(&AccessRecord::new(
+                content,
+                AccessRequester::Peer(peer),
+                AccessKind::Query,
+                10,
+                timestamp,
+            ))
+            .unwrap();
+        }
+
+        let records = log.between(1000, 2000).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].content_hash, hash);
+        assert_eq!(records[0].requester, AccessRequester::Peer(new_peer));
+        assert_eq!(records[1].content_hash, other);
+        assert_eq!(records[1].timestamp, 1500);
+
+        // old_peer was already seen before the window
+        assert_eq!(
+            log.first_seen_between(1000, 2000).unwrap(),
+            vec![AccessRequester::Peer(new_peer)]
+        );
+        assert!(log.first_seen_between(2000, 3000).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_prune() {
+        let log = setup_log();
+        let hash = content_hash(b"content");
+        let peer = test_peer_id();
+
+        for timestamp in [1000, 2000, 3000] {
+            log.record(&AccessRecord::new(
+                hash,
+                AccessRequester::Peer(peer),
+                AccessKind::Preview,
+                0,
+                timestamp,
+            ))
+            .unwrap();
+        }
+
+        assert_eq!(log.prune(2500).unwrap(), 2);
+        let records = log.for_content(&hash).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].timestamp, 3000);
+    }
+
+    #[test]
+    fn test_record_usage() {
+        let log = setup_log();
+        let hash = content_hash(b"content");
+
+        log.record_usage(&UsageRecord {
+            content_hash: hash,
+            bytes_read: 2048,
+            context_tokens: Some(32_000),
+            rating: Some(5),
+            timestamp: 2000,
+        })
+        .unwrap();
+        log.record_usage(&UsageRecord {
+            content_hash: hash,
+            bytes_read: 512,
+            context_tokens: None,
+            rating: None,
+            timestamp: 1000,
+        })
+        .unwrap();
+
+        let reports = log.usage_for_content(&hash).unwrap();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].bytes_read, 512);
+        assert_eq!(reports[0].rating, None);
+        assert_eq!(reports[1].context_tokens, Some(32_000));
+        assert_eq!(reports[1].rating, Some(5));
+        assert!(log
+            .usage_for_content(&content_hash(b"other"))
+            .unwrap()
+            .is_empty());
+
+        // Pruning covers usage reports too
+        assert_eq!(log.prune(1500).unwrap(), 1);
+        assert_eq!(log.usage_for_content(&hash).unwrap().len(), 1);
+    }
+}
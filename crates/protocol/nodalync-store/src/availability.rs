@@ -0,0 +1,218 @@
+//! Content availability storage.
+//!
+//! Keeps the outcome of the latest availability check of each content
+//! hash, with running totals, so a publisher can tell content that is
+//! fetchable from outside apart from content that only looks shared.
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{Hash, PeerId, Timestamp};
+
+use crate::error::{Result, StoreError};
+use crate::traits::AvailabilityStore;
+use crate::types::{AvailabilityCheck, AvailabilityRecord};
+
+const SELECT_COLUMNS: &str = "hash, checker, reachable, dialed, latency_ms, error, checked_at,
+     checks, failures, consecutive_failures";
+
+/// SQLite-based content availability store.
+pub struct SqliteAvailabilityStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteAvailabilityStore {
+    /// Create a new availability store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+}
+
+/// Read a row selected with `SELECT_COLUMNS`, skipping malformed ones.
+fn read_record(row: &Row<'_>) -> rusqlite::Result<Option<AvailabilityRecord>> {
+    let hash: Vec<u8> = row.get(0)?;
+    let checker: Vec<u8> = row.get(1)?;
+    let (Ok(hash), Ok(checker)) = (
+        <[u8; 32]>::try_from(hash.as_slice()),
+        <[u8; 20]>::try_from(checker.as_slice()),
+    ) else {
+        return Ok(None);
+    };
+
+    Ok(Some(AvailabilityRecord {
+        last: AvailabilityCheck {
+            hash: Hash(hash),
+            checker: PeerId(checker),
+            reachable: row.get(2)?,
+            dialed: row.get(3)?,
+            latency_ms: row.get::<_, Option<i64>>(4)?.map(|ms| ms as u32),
+            error: row.get(5)?,
+            checked_at: row.get::<_, i64>(6)? as Timestamp,
+        },
+        checks: row.get::<_, i64>(7)? as u64,
+        failures: row.get::<_, i64>(8)? as u64,
+        consecutive_failures: row.get::<_, i64>(9)? as u32,
+    }))
+}
+
+impl AvailabilityStore for SqliteAvailabilityStore {
+    fn record_check(&self, check: &AvailabilityCheck) -> Result<AvailabilityRecord> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let failed = i64::from(!check.reachable);
+        conn.execute(
+            "INSERT INTO availability_checks
+                (hash, checker, reachable, dialed, latency_ms, error, checked_at,
+                 checks, failures, consecutive_failures)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 1, ?8, ?8)
+             ON CONFLICT(hash) DO UPDATE SET
+                checker = excluded.checker,
+                reachable = excluded.reachable,
+                dialed = excluded.dialed,
+                latency_ms = excluded.latency_ms,
+                error = excluded.error,
+                checked_at = excluded.checked_at,
+                checks = checks + 1,
+                failures = failures + excluded.failures,
+                consecutive_failures = CASE WHEN excluded.reachable
+                    THEN 0 ELSE consecutive_failures + 1 END",
+            params![
+                check.hash.0.to_vec(),
+                check.checker.0.to_vec(),
+                check.reachable,
+                check.dialed,
+                check.latency_ms.map(i64::from),
+                check.error,
+                check.checked_at as i64,
+                failed,
+            ],
+        )?;
+
+        conn.query_row(
+            &format!("SELECT {SELECT_COLUMNS} FROM availability_checks WHERE hash = ?1"),
+            [check.hash.0.to_vec()],
+            read_record,
+        )?
+        .ok_or_else(|| StoreError::InvalidData("malformed availability record".to_string()))
+    }
+
+    fn get(&self, hash: &Hash) -> Result<Option<AvailabilityRecord>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let record = conn
+            .query_row(
+                &format!("SELECT {SELECT_COLUMNS} FROM availability_checks WHERE hash = ?1"),
+                [hash.0.to_vec()],
+                read_record,
+            )
+            .optional()?;
+
+        Ok(record.flatten())
+    }
+
+    fn list(&self) -> Result<Vec<AvailabilityRecord>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {SELECT_COLUMNS} FROM availability_checks
+             ORDER BY consecutive_failures DESC, checked_at DESC"
+        ))?;
+        let records = stmt
+            .query_map([], read_record)?
+            .filter_map(|r| r.ok().flatten())
+            .collect();
+
+        Ok(records)
+    }
+
+    fn remove(&self, hash: &Hash) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let removed = conn.execute(
+            "DELETE FROM availability_checks WHERE hash = ?1",
+            [hash.0.to_vec()],
+        )?;
+
+        Ok(removed > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::content_hash;
+
+    fn setup_store() -> SqliteAvailabilityStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteAvailabilityStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    fn check(hash: Hash, reachable: bool, checked_at: Timestamp) -> AvailabilityCheck {
+        AvailabilityCheck {
+            hash,
+            checker: PeerId([7u8; 20]),
+            reachable,
+            dialed: reachable.then(|| "/ip4/203.0.113.5/tcp/9000".to_string()),
+            latency_ms: reachable.then_some(42),
+            error: (!reachable).then(|| "dial failed".to_string()),
+            checked_at,
+        }
+    }
+
+    #[test]
+    fn test_record_check_totals() {
+        let store = setup_store();
+        let hash = content_hash(b"shared");
+
+        let record = store.record_check(&check(hash, false, 1_000)).unwrap();
+        assert_eq!(
+            (record.checks, record.failures, record.consecutive_failures),
+            (1, 1, 1)
+        );
+        let record = store.record_check(&check(hash, false, 2_000)).unwrap();
+        assert_eq!(record.consecutive_failures, 2);
+        assert_eq!(record.last.error.as_deref(), Some("dial failed"));
+
+        // A success resets the streak but keeps the totals
+        let record = store.record_check(&check(hash, true, 3_000)).unwrap();
+        assert_eq!(
+            (record.checks, record.failures, record.consecutive_failures),
+            (3, 2, 0)
+        );
+        assert_eq!(record.last.latency_ms, Some(42));
+        assert_eq!(record.last.error, None);
+        assert_eq!(store.get(&hash).unwrap(), Some(record));
+    }
+
+    #[test]
+    fn test_list_and_remove() {
+        let store = setup_store();
+        let ok = content_hash(b"ok");
+        let broken = content_hash(b"broken");
+        store.record_check(&check(ok, true, 5_000)).unwrap();
+        store.record_check(&check(broken, false, 1_000)).unwrap();
+
+        // Failing content comes first
+        let hashes: Vec<Hash> = store.list().unwrap().iter().map(|r| r.last.hash).collect();
+        assert_eq!(hashes, vec![broken, ok]);
+
+        assert!(store.remove(&broken).unwrap());
+        assert!(!store.remove(&broken).unwrap());
+        assert_eq!(store.get(&broken).unwrap(), None);
+        assert_eq!(store.list().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,335 @@
+//! Storage backends for the stores that can be shared between nodes.
+//!
+//! Manifests, peers, channels and the settlement queue live in the node's
+//! SQLite database by default. With the `postgres` feature and a
+//! `database_url` (see [`crate::NodeStateConfig::with_database_url`]), they
+//! live in a Postgres database that several nodes share instead. Each
+//! backend implements the same store trait and inherent methods as its
+//! SQLite store, so callers don't need to know which one is in use.
+
+use nodalync_crypto::{Hash, PeerId, Timestamp};
+use nodalync_types::{Amount, CanonicalVersion, Channel, Manifest, Payment};
+
+use crate::error::Result;
+use crate::manifest_index::ManifestIndexStats;
+use crate::traits::{ChannelStore, ManifestStore, PeerStore, SettlementQueueStore};
+use crate::types::{
+    ChannelCheckpoint, LatencyStats, ManifestFilter, PaymentDirection, PaymentNonces, PeerInfo,
+    QueuedDistribution, WalletTransaction, WalletTransactionKind,
+};
+use crate::{SqliteChannelStore, SqliteManifestStore, SqlitePeerStore, SqliteSettlementQueue};
+
+#[cfg(feature = "postgres")]
+use crate::postgres::{
+    AdvisoryLock, PgChannelStore, PgManifestStore, PgPeerStore, PgSettlementQueue,
+};
+
+/// Call the same method on whichever store a backend holds.
+macro_rules! dispatch {
+    ($self:expr, $store:ident => $call:expr) => {
+        match $self {
+            Self::Sqlite($store) => $call,
+            #[cfg(feature = "postgres")]
+            Self::Postgres($store) => $call,
+        }
+    };
+}
+
+/// Manifest storage backend.
+pub enum ManifestBackend {
+    /// The node's own SQLite database.
+    Sqlite(SqliteManifestStore),
+    /// A Postgres database shared with other nodes.
+    #[cfg(feature = "postgres")]
+    Postgres(PgManifestStore),
+}
+
+impl ManifestBackend {
+    /// Set the memory budget of the manifest index, in bytes.
+    pub fn set_index_capacity(&self, max_bytes: usize) {
+        dispatch!(self, store => store.set_index_capacity(max_bytes))
+    }
+
+    /// Hit, miss and size counters of the manifest index.
+    pub fn index_stats(&self) -> ManifestIndexStats {
+        dispatch!(self, store => store.index_stats())
+    }
+
+    /// Keep the manifests of these hashes in memory, in place of the
+    /// previous set. Returns the number of manifests held.
+    pub fn retain_hot(&self, hashes: &[Hash]) -> Result<usize> {
+        dispatch!(self, store => store.retain_hot(hashes))
+    }
+
+    /// Whether a manifest is held in memory.
+    pub fn is_hot(&self, hash: &Hash) -> bool {
+        dispatch!(self, store => store.is_hot(hash))
+    }
+}
+
+impl ManifestStore for ManifestBackend {
+    fn store(&self, manifest: &Manifest) -> Result<()> {
+        dispatch!(self, store => store.store(manifest))
+    }
+
+    fn store_batch(&self, manifests: &[Manifest]) -> Result<usize> {
+        dispatch!(self, store => store.store_batch(manifests))
+    }
+
+    fn load(&self, hash: &Hash) -> Result<Option<Manifest>> {
+        dispatch!(self, store => store.load(hash))
+    }
+
+    fn update(&self, manifest: &Manifest) -> Result<()> {
+        dispatch!(self, store => store.update(manifest))
+    }
+
+    fn delete(&self, hash: &Hash) -> Result<()> {
+        dispatch!(self, store => store.delete(hash))
+    }
+
+    fn list(&self, filter: ManifestFilter) -> Result<Vec<Manifest>> {
+        dispatch!(self, store => store.list(filter))
+    }
+
+    fn get_versions(&self, version_root: &Hash) -> Result<Vec<Manifest>> {
+        dispatch!(self, store => store.get_versions(version_root))
+    }
+
+    fn set_canonical(&self, pointer: &CanonicalVersion) -> Result<bool> {
+        dispatch!(self, store => store.set_canonical(pointer))
+    }
+
+    fn get_canonical(&self, version_root: &Hash) -> Result<Option<CanonicalVersion>> {
+        dispatch!(self, store => store.get_canonical(version_root))
+    }
+}
+
+/// Peer storage backend.
+#[derive(Clone)]
+pub enum PeerBackend {
+    /// The node's own SQLite database.
+    Sqlite(SqlitePeerStore),
+    /// A Postgres database shared with other nodes.
+    #[cfg(feature = "postgres")]
+    Postgres(PgPeerStore),
+}
+
+impl PeerStore for PeerBackend {
+    fn upsert(&self, peer: &PeerInfo) -> Result<()> {
+        dispatch!(self, store => store.upsert(peer))
+    }
+
+    fn get(&self, peer_id: &PeerId) -> Result<Option<PeerInfo>> {
+        dispatch!(self, store => store.get(peer_id))
+    }
+
+    fn list(&self) -> Result<Vec<PeerInfo>> {
+        dispatch!(self, store => store.list())
+    }
+
+    fn update_last_seen(&self, peer_id: &PeerId, timestamp: Timestamp) -> Result<()> {
+        dispatch!(self, store => store.update_last_seen(peer_id, timestamp))
+    }
+
+    fn update_reputation(&self, peer_id: &PeerId, delta: i64) -> Result<()> {
+        dispatch!(self, store => store.update_reputation(peer_id, delta))
+    }
+
+    fn record_latency(
+        &self,
+        peer_id: &PeerId,
+        rtt_ms: Option<u32>,
+        timestamp: Timestamp,
+    ) -> Result<LatencyStats> {
+        dispatch!(self, store => store.record_latency(peer_id, rtt_ms, timestamp))
+    }
+
+    fn delete(&self, peer_id: &PeerId) -> Result<()> {
+        dispatch!(self, store => store.delete(peer_id))
+    }
+}
+
+/// Channel storage backend.
+pub enum ChannelBackend {
+    /// The node's own SQLite database.
+    Sqlite(SqliteChannelStore),
+    /// A Postgres database shared with other nodes.
+    #[cfg(feature = "postgres")]
+    Postgres(PgChannelStore),
+}
+
+impl ChannelBackend {
+    /// Delete a channel and all its payments.
+    pub fn delete(&self, peer: &PeerId) -> Result<()> {
+        dispatch!(self, store => store.delete(peer))
+    }
+
+    /// Count channels that are not yet closed.
+    pub fn count_active(&self) -> Result<usize> {
+        dispatch!(self, store => store.count_active())
+    }
+
+    /// Record a signed channel state.
+    pub fn checkpoint(&self, checkpoint: &ChannelCheckpoint) -> Result<()> {
+        dispatch!(self, store => store.checkpoint(checkpoint))
+    }
+
+    /// Get the checkpoint with the highest nonce for a channel.
+    pub fn latest_checkpoint(&self, channel_id: &Hash) -> Result<Option<ChannelCheckpoint>> {
+        dispatch!(self, store => store.latest_checkpoint(channel_id))
+    }
+
+    /// List all checkpoints for a channel, ordered by nonce.
+    pub fn checkpoints(&self, channel_id: &Hash) -> Result<Vec<ChannelCheckpoint>> {
+        dispatch!(self, store => store.checkpoints(channel_id))
+    }
+
+    /// Find nonce ranges missing between consecutive checkpoints.
+    pub fn checkpoint_gaps(&self, channel_id: &Hash) -> Result<Vec<(u64, u64)>> {
+        dispatch!(self, store => store.checkpoint_gaps(channel_id))
+    }
+
+    /// Delete checkpoints for a channel with a nonce below `nonce`.
+    pub fn prune_checkpoints(&self, channel_id: &Hash, nonce: u64) -> Result<usize> {
+        dispatch!(self, store => store.prune_checkpoints(channel_id, nonce))
+    }
+
+    /// Record a payment nonce used on a channel.
+    pub fn record_payment_nonce(
+        &self,
+        channel_id: &Hash,
+        peer: &PeerId,
+        direction: PaymentDirection,
+        nonce: u64,
+        timestamp: Timestamp,
+    ) -> Result<()> {
+        dispatch!(self, store => store.record_payment_nonce(channel_id, peer, direction, nonce, timestamp))
+    }
+
+    /// Get the highest payment nonces recorded for a channel.
+    pub fn payment_nonces(&self, channel_id: &Hash) -> Result<Option<PaymentNonces>> {
+        dispatch!(self, store => store.payment_nonces(channel_id))
+    }
+}
+
+impl ChannelStore for ChannelBackend {
+    fn create(&self, peer: &PeerId, channel: Channel) -> Result<()> {
+        dispatch!(self, store => store.create(peer, channel))
+    }
+
+    fn get(&self, peer: &PeerId) -> Result<Option<Channel>> {
+        dispatch!(self, store => store.get(peer))
+    }
+
+    fn update(&self, peer: &PeerId, channel: &Channel) -> Result<()> {
+        dispatch!(self, store => store.update(peer, channel))
+    }
+
+    fn list_open(&self) -> Result<Vec<(PeerId, Channel)>> {
+        dispatch!(self, store => store.list_open())
+    }
+
+    fn clear_all(&self) -> Result<()> {
+        dispatch!(self, store => store.clear_all())
+    }
+
+    fn add_payment(&self, peer: &PeerId, payment: Payment) -> Result<()> {
+        dispatch!(self, store => store.add_payment(peer, payment))
+    }
+
+    fn get_pending_payments(&self, peer: &PeerId) -> Result<Vec<Payment>> {
+        dispatch!(self, store => store.get_pending_payments(peer))
+    }
+
+    fn clear_payments(&self, peer: &PeerId, payment_ids: &[Hash]) -> Result<()> {
+        dispatch!(self, store => store.clear_payments(peer, payment_ids))
+    }
+}
+
+/// Settlement queue backend.
+pub enum SettlementBackend {
+    /// The node's own SQLite database.
+    Sqlite(SqliteSettlementQueue),
+    /// A Postgres database shared with other nodes.
+    #[cfg(feature = "postgres")]
+    Postgres(PgSettlementQueue),
+}
+
+impl SettlementBackend {
+    /// Take the right to settle the queue, or `None` if another node
+    /// sharing it is settling.
+    ///
+    /// A local queue has a single settler, so this always succeeds there.
+    /// Hold the guard until the batch is marked settled.
+    pub fn try_lock_batch(&self) -> Result<Option<BatchLock>> {
+        match self {
+            Self::Sqlite(_) => Ok(Some(BatchLock { _lock: None })),
+            #[cfg(feature = "postgres")]
+            Self::Postgres(queue) => Ok(queue
+                .try_lock_batch()?
+                .map(|lock| BatchLock { _lock: Some(lock) })),
+        }
+    }
+
+    /// Get count of pending distributions.
+    pub fn pending_count(&self) -> Result<u64> {
+        dispatch!(self, store => store.pending_count())
+    }
+
+    /// Record an on-chain transaction submitted by this node.
+    pub fn record_transaction(&self, tx: &WalletTransaction) -> Result<()> {
+        dispatch!(self, store => store.record_transaction(tx))
+    }
+
+    /// Get the most recent on-chain transactions, newest first.
+    pub fn recent_transactions(&self, limit: u32) -> Result<Vec<WalletTransaction>> {
+        dispatch!(self, store => store.recent_transactions(limit))
+    }
+
+    /// Get the most recent wallet transaction of a kind.
+    pub fn latest_transaction(
+        &self,
+        kind: WalletTransactionKind,
+    ) -> Result<Option<WalletTransaction>> {
+        dispatch!(self, store => store.latest_transaction(kind))
+    }
+}
+
+impl SettlementQueueStore for SettlementBackend {
+    fn enqueue(&self, distribution: QueuedDistribution) -> Result<()> {
+        dispatch!(self, store => store.enqueue(distribution))
+    }
+
+    fn get_pending(&self) -> Result<Vec<QueuedDistribution>> {
+        dispatch!(self, store => store.get_pending())
+    }
+
+    fn get_pending_for(&self, recipient: &PeerId) -> Result<Vec<QueuedDistribution>> {
+        dispatch!(self, store => store.get_pending_for(recipient))
+    }
+
+    fn get_pending_total(&self) -> Result<Amount> {
+        dispatch!(self, store => store.get_pending_total())
+    }
+
+    fn mark_settled(&self, payment_ids: &[Hash], batch_id: &Hash) -> Result<()> {
+        dispatch!(self, store => store.mark_settled(payment_ids, batch_id))
+    }
+
+    fn get_last_settlement_time(&self) -> Result<Option<Timestamp>> {
+        dispatch!(self, store => store.get_last_settlement_time())
+    }
+
+    fn set_last_settlement_time(&self, timestamp: Timestamp) -> Result<()> {
+        dispatch!(self, store => store.set_last_settlement_time(timestamp))
+    }
+}
+
+/// The right to settle the queue, released on drop.
+pub struct BatchLock {
+    #[cfg(feature = "postgres")]
+    _lock: Option<AdvisoryLock>,
+    #[cfg(not(feature = "postgres"))]
+    _lock: Option<()>,
+}
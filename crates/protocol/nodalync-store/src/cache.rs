@@ -15,7 +15,7 @@ use nodalync_wire::payload::PaymentReceipt;
 
 use crate::error::{Result, StoreError};
 use crate::traits::CacheStore;
-use crate::types::CachedContent;
+use crate::types::{CachedContent, QueryReceipt};
 
 /// Hybrid filesystem + SQLite cache store.
 ///
@@ -67,6 +67,10 @@ impl FsCacheStore {
                 timestamp: 0,
                 channel_nonce: 0,
                 distributor_signature: nodalync_crypto::Signature::from_bytes([0u8; 64]),
+                app_fee: 0,
+                app_fee_recipient: None,
+                referral_fee: 0,
+                referrer: None,
             });
 
         Ok(CacheMetadata {
@@ -90,7 +94,7 @@ struct CacheMetadata {
 }
 
 impl CacheStore for FsCacheStore {
-    fn cache(&mut self, entry: CachedContent) -> Result<()> {
+    fn cache(&self, entry: CachedContent) -> Result<()> {
         // Store content on filesystem
         self.ensure_parent_dir(&entry.hash)?;
         let path = self.content_path(&entry.hash);
@@ -108,17 +112,14 @@ impl CacheStore for FsCacheStore {
         let payment_receipt_json = serde_json::to_string(&entry.payment_proof)?;
         let size_bytes = entry.content.len() as i64;
 
-        conn.execute(
-            "INSERT OR REPLACE INTO cache (hash, source_peer, queried_at, size_bytes, payment_receipt)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![
+        conn.prepare_cached("INSERT OR REPLACE INTO cache (hash, source_peer, queried_at, size_bytes, payment_receipt)
+             VALUES (?1, ?2, ?3, ?4, ?5)")?.execute(params![
                 hash_bytes,
                 source_peer_bytes,
                 entry.queried_at as i64,
                 size_bytes,
                 payment_receipt_json
-            ],
-        )?;
+            ])?;
 
         Ok(())
     }
@@ -132,12 +133,11 @@ impl CacheStore for FsCacheStore {
         let hash_bytes = hash.0.to_vec();
 
         let metadata = conn
-            .query_row(
+            .prepare_cached(
                 "SELECT hash, source_peer, queried_at, size_bytes, payment_receipt
                  FROM cache WHERE hash = ?1",
-                [&hash_bytes],
-                Self::deserialize_metadata,
-            )
+            )?
+            .query_row([&hash_bytes], Self::deserialize_metadata)
             .optional()?;
 
         let metadata = match metadata {
@@ -187,15 +187,13 @@ impl CacheStore for FsCacheStore {
             Err(_) => return false,
         };
         let hash_bytes = hash.0.to_vec();
-        conn.query_row("SELECT 1 FROM cache WHERE hash = ?1", [hash_bytes], |_| {
-            Ok(true)
-        })
-        .optional()
-        .unwrap_or(None)
-        .unwrap_or(false)
+        conn.prepare_cached("SELECT 1 FROM cache WHERE hash = ?1")
+            .and_then(|mut stmt| stmt.query_row([hash_bytes], |_| Ok(true)).optional())
+            .unwrap_or(None)
+            .unwrap_or(false)
     }
 
-    fn evict(&mut self, max_size_bytes: u64) -> Result<u64> {
+    fn evict(&self, max_size_bytes: u64) -> Result<u64> {
         let conn = self
             .conn
             .lock()
@@ -262,7 +260,22 @@ impl CacheStore for FsCacheStore {
         Ok(freed)
     }
 
-    fn clear(&mut self) -> Result<()> {
+    fn remove(&self, hash: &Hash) -> Result<bool> {
+        let path = self.content_path(hash);
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+        let deleted = conn.execute("DELETE FROM cache WHERE hash = ?1", [hash.0.to_vec()])?;
+
+        Ok(deleted > 0)
+    }
+
+    fn clear(&self) -> Result<()> {
         // Clear all cached content files
         if self.cache_dir.exists() {
             for entry in fs::read_dir(&self.cache_dir)?.flatten() {
@@ -308,7 +321,7 @@ impl FsCacheStore {
     }
 
     /// Update the queried_at timestamp (for LRU refresh on access).
-    pub fn touch(&mut self, hash: &Hash, timestamp: Timestamp) -> Result<()> {
+    pub fn touch(&self, hash: &Hash, timestamp: Timestamp) -> Result<()> {
         let conn = self
             .conn
             .lock()
@@ -320,6 +333,56 @@ impl FsCacheStore {
         )?;
         Ok(())
     }
+
+    /// Get the payment receipts of cached queries, newest first.
+    pub fn receipts(&self) -> Result<Vec<QueryReceipt>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT hash, source_peer, queried_at, size_bytes, payment_receipt
+             FROM cache ORDER BY queried_at DESC",
+        )?;
+        let receipts = stmt
+            .query_map([], Self::deserialize_metadata)?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+            .into_iter()
+            .map(|metadata| QueryReceipt {
+                hash: metadata.hash,
+                source_peer: metadata.source_peer,
+                queried_at: metadata.queried_at,
+                receipt: metadata.payment_receipt,
+            })
+            .collect();
+
+        Ok(receipts)
+    }
+
+    /// Get the most recently queried content hashes, newest first.
+    ///
+    /// Acts as the node's query history.
+    pub fn recent(&self, limit: u32) -> Result<Vec<(Hash, Timestamp)>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt =
+            conn.prepare("SELECT hash, queried_at FROM cache ORDER BY queried_at DESC LIMIT ?1")?;
+
+        let entries: Vec<(Hash, Timestamp)> = stmt
+            .query_map([limit], |row| {
+                let hash_bytes: Vec<u8> = row.get(0)?;
+                let queried_at: i64 = row.get(1)?;
+                Ok((bytes_to_hash(&hash_bytes), queried_at as Timestamp))
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(entries)
+    }
 }
 
 /// Convert bytes to Hash.
@@ -372,13 +435,17 @@ mod tests {
                 timestamp: 1234567890,
                 channel_nonce: 1,
                 distributor_signature: Signature::from_bytes([0u8; 64]),
+                app_fee: 0,
+                app_fee_recipient: None,
+                referral_fee: 0,
+                referrer: None,
             },
         }
     }
 
     #[test]
     fn test_cache_and_get() {
-        let (mut store, _temp) = setup_store();
+        let (store, _temp) = setup_store();
         let cached = test_cached_content(b"test content");
 
         store.cache(cached.clone()).unwrap();
@@ -394,7 +461,7 @@ mod tests {
 
     #[test]
     fn test_is_cached() {
-        let (mut store, _temp) = setup_store();
+        let (store, _temp) = setup_store();
         let cached = test_cached_content(b"content");
 
         assert!(!store.is_cached(&cached.hash));
@@ -414,7 +481,7 @@ mod tests {
 
     #[test]
     fn test_clear() {
-        let (mut store, _temp) = setup_store();
+        let (store, _temp) = setup_store();
 
         store.cache(test_cached_content(b"content1")).unwrap();
         store.cache(test_cached_content(b"content2")).unwrap();
@@ -426,9 +493,25 @@ mod tests {
         assert_eq!(store.count().unwrap(), 0);
     }
 
+    #[test]
+    fn test_remove() {
+        let (store, _temp) = setup_store();
+
+        let entry = test_cached_content(b"content1");
+        let hash = entry.hash;
+        store.cache(entry).unwrap();
+        store.cache(test_cached_content(b"content2")).unwrap();
+
+        assert!(store.remove(&hash).unwrap());
+        assert!(!store.is_cached(&hash));
+        assert!(store.get(&hash).unwrap().is_none());
+        assert_eq!(store.count().unwrap(), 1);
+        assert!(!store.remove(&hash).unwrap());
+    }
+
     #[test]
     fn test_total_size() {
-        let (mut store, _temp) = setup_store();
+        let (store, _temp) = setup_store();
 
         let content1 = b"content1";
         let content2 = b"content2";
@@ -442,7 +525,7 @@ mod tests {
 
     #[test]
     fn test_evict_lru() {
-        let (mut store, _temp) = setup_store();
+        let (store, _temp) = setup_store();
 
         // Add entries with different timestamps
         let mut c1 = test_cached_content(b"oldest");
@@ -473,7 +556,7 @@ mod tests {
 
     #[test]
     fn test_evict_no_op() {
-        let (mut store, _temp) = setup_store();
+        let (store, _temp) = setup_store();
 
         store.cache(test_cached_content(b"small")).unwrap();
 
@@ -485,7 +568,7 @@ mod tests {
 
     #[test]
     fn test_touch() {
-        let (mut store, _temp) = setup_store();
+        let (store, _temp) = setup_store();
 
         let mut cached = test_cached_content(b"content");
         cached.queried_at = 1000;
@@ -508,7 +591,7 @@ mod tests {
 
     #[test]
     fn test_replace_existing() {
-        let (mut store, _temp) = setup_store();
+        let (store, _temp) = setup_store();
 
         let mut cached1 = test_cached_content(b"original");
         cached1.queried_at = 1000;
@@ -525,4 +608,42 @@ mod tests {
         let loaded = store.get(&cached2.hash).unwrap().unwrap();
         assert_eq!(loaded.queried_at, 2000);
     }
+
+    #[test]
+    fn test_recent() {
+        let (store, _temp) = setup_store();
+
+        let mut first = test_cached_content(b"first");
+        first.queried_at = 1000;
+        let mut second = test_cached_content(b"second");
+        second.queried_at = 2000;
+        let mut third = test_cached_content(b"third");
+        third.queried_at = 3000;
+
+        store.cache(first).unwrap();
+        store.cache(third.clone()).unwrap();
+        store.cache(second.clone()).unwrap();
+
+        let recent = store.recent(2).unwrap();
+        assert_eq!(recent, vec![(third.hash, 3000), (second.hash, 2000)]);
+    }
+
+    #[test]
+    fn test_receipts() {
+        let (store, _temp) = setup_store();
+
+        let mut first = test_cached_content(b"first");
+        first.queried_at = 1000;
+        let mut second = test_cached_content(b"second");
+        second.queried_at = 2000;
+        store.cache(first.clone()).unwrap();
+        store.cache(second.clone()).unwrap();
+
+        let receipts = store.receipts().unwrap();
+        assert_eq!(receipts.len(), 2);
+        assert_eq!(receipts[0].hash, second.hash);
+        assert_eq!(receipts[0].receipt, second.payment_proof);
+        assert_eq!(receipts[1].source_peer, first.source_peer);
+        assert_eq!(receipts[1].queried_at, 1000);
+    }
 }
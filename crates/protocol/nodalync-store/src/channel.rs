@@ -1,6 +1,10 @@
 //! Payment channel storage.
 //!
 //! This module implements storage for payment channels and pending payments.
+//! Channel states are also checkpointed write-ahead so a crash between
+//! producing a signed state and persisting the channel can be recovered,
+//! and the highest payment nonce in each direction is tracked so nonces
+//! are never reused or replayed across restarts.
 
 use rusqlite::{params, Connection, OptionalExtension};
 use std::sync::{Arc, Mutex};
@@ -12,6 +16,7 @@ use nodalync_types::{
 
 use crate::error::{Result, StoreError};
 use crate::traits::ChannelStore;
+use crate::types::{ChannelCheckpoint, PaymentDirection, PaymentNonces};
 
 /// SQLite-based channel store.
 pub struct SqliteChannelStore {
@@ -162,7 +167,7 @@ impl SqliteChannelStore {
 }
 
 impl ChannelStore for SqliteChannelStore {
-    fn create(&mut self, peer: &PeerId, channel: Channel) -> Result<()> {
+    fn create(&self, peer: &PeerId, channel: Channel) -> Result<()> {
         let conn = self
             .conn
             .lock()
@@ -231,7 +236,7 @@ impl ChannelStore for SqliteChannelStore {
         }
     }
 
-    fn update(&mut self, peer: &PeerId, channel: &Channel) -> Result<()> {
+    fn update(&self, peer: &PeerId, channel: &Channel) -> Result<()> {
         let conn = self
             .conn
             .lock()
@@ -312,19 +317,21 @@ impl ChannelStore for SqliteChannelStore {
         Ok(channels_with_payments)
     }
 
-    fn clear_all(&mut self) -> Result<()> {
+    fn clear_all(&self) -> Result<()> {
         let conn = self
             .conn
             .lock()
             .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
         // Try to clear pending_payments if the table exists (ignore errors if it doesn't)
         let _ = conn.execute("DELETE FROM pending_payments", []);
-        // Clear channels table
+        // Clear checkpoints, nonces and channels
+        conn.execute("DELETE FROM channel_checkpoints", [])?;
+        conn.execute("DELETE FROM payment_nonces", [])?;
         conn.execute("DELETE FROM channels", [])?;
         Ok(())
     }
 
-    fn add_payment(&mut self, peer: &PeerId, payment: Payment) -> Result<()> {
+    fn add_payment(&self, peer: &PeerId, payment: Payment) -> Result<()> {
         let conn = self
             .conn
             .lock()
@@ -359,7 +366,7 @@ impl ChannelStore for SqliteChannelStore {
         self.load_pending_payments(&conn, peer)
     }
 
-    fn clear_payments(&mut self, peer: &PeerId, payment_ids: &[Hash]) -> Result<()> {
+    fn clear_payments(&self, peer: &PeerId, payment_ids: &[Hash]) -> Result<()> {
         let conn = self
             .conn
             .lock()
@@ -397,7 +404,7 @@ impl SqliteChannelStore {
     }
 
     /// Delete a channel and all its payments.
-    pub fn delete(&mut self, peer: &PeerId) -> Result<()> {
+    pub fn delete(&self, peer: &PeerId) -> Result<()> {
         let conn = self
             .conn
             .lock()
@@ -408,10 +415,253 @@ impl SqliteChannelStore {
             "DELETE FROM payments WHERE channel_peer = ?1",
             [&peer_bytes],
         )?;
+        conn.execute(
+            "DELETE FROM channel_checkpoints WHERE peer_id = ?1",
+            [&peer_bytes],
+        )?;
+        conn.execute(
+            "DELETE FROM payment_nonces WHERE peer_id = ?1",
+            [&peer_bytes],
+        )?;
         conn.execute("DELETE FROM channels WHERE peer_id = ?1", [&peer_bytes])?;
 
         Ok(())
     }
+
+    /// Count channels that are not yet closed.
+    ///
+    /// Includes channels still opening, closing, or under dispute, since all
+    /// of them keep a deposit locked.
+    pub fn count_active(&self) -> Result<usize> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM channels WHERE state != ?1",
+            [ChannelState::Closed as u8],
+            |row| row.get(0),
+        )?;
+
+        Ok(count as usize)
+    }
+
+    /// Record a signed channel state.
+    ///
+    /// Must be called before the corresponding `update()` so the latest
+    /// signed state survives a crash mid-update. Re-recording the same
+    /// nonce overwrites the previous checkpoint.
+    pub fn checkpoint(&self, checkpoint: &ChannelCheckpoint) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO channel_checkpoints
+             (channel_id, nonce, peer_id, my_balance, their_balance, signature, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                checkpoint.channel_id.0.to_vec(),
+                checkpoint.nonce as i64,
+                checkpoint.peer_id.0.to_vec(),
+                checkpoint.my_balance as i64,
+                checkpoint.their_balance as i64,
+                checkpoint.signature.0.to_vec(),
+                checkpoint.created_at as i64,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Get the checkpoint with the highest nonce for a channel.
+    pub fn latest_checkpoint(&self, channel_id: &Hash) -> Result<Option<ChannelCheckpoint>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let checkpoint = conn
+            .query_row(
+                "SELECT channel_id, nonce, peer_id, my_balance, their_balance, signature, created_at
+                 FROM channel_checkpoints WHERE channel_id = ?1
+                 ORDER BY nonce DESC LIMIT 1",
+                [channel_id.0.to_vec()],
+                Self::deserialize_checkpoint,
+            )
+            .optional()?;
+
+        Ok(checkpoint)
+    }
+
+    /// List all checkpoints for a channel, ordered by nonce.
+    pub fn checkpoints(&self, channel_id: &Hash) -> Result<Vec<ChannelCheckpoint>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT channel_id, nonce, peer_id, my_balance, their_balance, signature, created_at
+             FROM channel_checkpoints WHERE channel_id = ?1
+             ORDER BY nonce ASC",
+        )?;
+
+        let checkpoints = stmt
+            .query_map([channel_id.0.to_vec()], Self::deserialize_checkpoint)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(checkpoints)
+    }
+
+    /// Find nonce ranges missing between consecutive checkpoints.
+    ///
+    /// Returns inclusive `(first, last)` ranges of nonces that were never
+    /// checkpointed locally, meaning the counterparty advanced the channel
+    /// through states this node did not record.
+    pub fn checkpoint_gaps(&self, channel_id: &Hash) -> Result<Vec<(u64, u64)>> {
+        let checkpoints = self.checkpoints(channel_id)?;
+
+        let gaps = checkpoints
+            .windows(2)
+            .filter(|pair| pair[1].nonce > pair[0].nonce + 1)
+            .map(|pair| (pair[0].nonce + 1, pair[1].nonce - 1))
+            .collect();
+
+        Ok(gaps)
+    }
+
+    /// Delete checkpoints for a channel with a nonce below `nonce`.
+    ///
+    /// Used once the channel has been resynced with the counterparty, so
+    /// resolved gaps are not reported again.
+    pub fn prune_checkpoints(&self, channel_id: &Hash, nonce: u64) -> Result<usize> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let deleted = conn.execute(
+            "DELETE FROM channel_checkpoints WHERE channel_id = ?1 AND nonce < ?2",
+            params![channel_id.0.to_vec(), nonce as i64],
+        )?;
+
+        Ok(deleted)
+    }
+
+    /// Record a payment nonce used on a channel.
+    ///
+    /// Nonces must strictly increase per direction: recording a nonce at or
+    /// below the highest one already recorded fails with
+    /// [`StoreError::StaleNonce`]. The check and update happen in a single
+    /// statement, so two concurrent requests cannot both claim a nonce.
+    pub fn record_payment_nonce(
+        &self,
+        channel_id: &Hash,
+        peer: &PeerId,
+        direction: PaymentDirection,
+        nonce: u64,
+        timestamp: Timestamp,
+    ) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        conn.execute(
+            "INSERT OR IGNORE INTO payment_nonces
+             (channel_id, peer_id, last_sent, last_received, updated_at)
+             VALUES (?1, ?2, 0, 0, ?3)",
+            params![channel_id.0.to_vec(), peer.0.to_vec(), timestamp as i64],
+        )?;
+
+        let column = Self::nonce_column(direction);
+        let updated = conn.execute(
+            &format!(
+                "UPDATE payment_nonces SET {column} = ?1, updated_at = ?2
+                 WHERE channel_id = ?3 AND {column} < ?1"
+            ),
+            params![nonce as i64, timestamp as i64, channel_id.0.to_vec()],
+        )?;
+
+        if updated == 0 {
+            let last: i64 = conn.query_row(
+                &format!("SELECT {column} FROM payment_nonces WHERE channel_id = ?1"),
+                [channel_id.0.to_vec()],
+                |row| row.get(0),
+            )?;
+            return Err(StoreError::StaleNonce {
+                nonce,
+                last: last as u64,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Get the highest payment nonces recorded for a channel.
+    pub fn payment_nonces(&self, channel_id: &Hash) -> Result<Option<PaymentNonces>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let nonces = conn
+            .query_row(
+                "SELECT channel_id, peer_id, last_sent, last_received, updated_at
+                 FROM payment_nonces WHERE channel_id = ?1",
+                [channel_id.0.to_vec()],
+                |row| {
+                    let channel_id_bytes: Vec<u8> = row.get(0)?;
+                    let peer_id_bytes: Vec<u8> = row.get(1)?;
+                    let last_sent: i64 = row.get(2)?;
+                    let last_received: i64 = row.get(3)?;
+                    let updated_at: i64 = row.get(4)?;
+
+                    Ok(PaymentNonces {
+                        channel_id: bytes_to_hash(&channel_id_bytes),
+                        peer_id: bytes_to_peer_id(&peer_id_bytes),
+                        last_sent: last_sent as u64,
+                        last_received: last_received as u64,
+                        updated_at: updated_at as u64,
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(nonces)
+    }
+
+    /// Column holding the highest nonce for a direction.
+    fn nonce_column(direction: PaymentDirection) -> &'static str {
+        match direction {
+            PaymentDirection::Sent => "last_sent",
+            PaymentDirection::Received => "last_received",
+        }
+    }
+
+    /// Deserialize a checkpoint from a database row.
+    fn deserialize_checkpoint(row: &rusqlite::Row) -> rusqlite::Result<ChannelCheckpoint> {
+        let channel_id_bytes: Vec<u8> = row.get(0)?;
+        let nonce: i64 = row.get(1)?;
+        let peer_id_bytes: Vec<u8> = row.get(2)?;
+        let my_balance: i64 = row.get(3)?;
+        let their_balance: i64 = row.get(4)?;
+        let signature_bytes: Vec<u8> = row.get(5)?;
+        let created_at: i64 = row.get(6)?;
+
+        Ok(ChannelCheckpoint {
+            channel_id: bytes_to_hash(&channel_id_bytes),
+            peer_id: bytes_to_peer_id(&peer_id_bytes),
+            nonce: nonce as u64,
+            my_balance: my_balance as Amount,
+            their_balance: their_balance as Amount,
+            signature: bytes_to_signature(&signature_bytes),
+            created_at: created_at as Timestamp,
+        })
+    }
 }
 
 /// Convert bytes to Hash.
@@ -499,7 +749,7 @@ mod tests {
 
     #[test]
     fn test_create_and_get() {
-        let mut store = setup_store();
+        let store = setup_store();
         let peer = test_peer_id();
         let channel = test_channel(peer);
 
@@ -516,7 +766,7 @@ mod tests {
 
     #[test]
     fn test_create_duplicate() {
-        let mut store = setup_store();
+        let store = setup_store();
         let peer = test_peer_id();
         let channel = test_channel(peer);
 
@@ -537,7 +787,7 @@ mod tests {
 
     #[test]
     fn test_update() {
-        let mut store = setup_store();
+        let store = setup_store();
         let peer = test_peer_id();
         let mut channel = test_channel(peer);
 
@@ -557,7 +807,7 @@ mod tests {
 
     #[test]
     fn test_update_nonexistent() {
-        let mut store = setup_store();
+        let store = setup_store();
         let peer = test_peer_id();
         let channel = test_channel(peer);
 
@@ -567,7 +817,7 @@ mod tests {
 
     #[test]
     fn test_list_open() {
-        let mut store = setup_store();
+        let store = setup_store();
 
         // Create opening channel
         let peer1 = test_peer_id();
@@ -592,9 +842,30 @@ mod tests {
         assert_eq!(open_channels[0].0, peer2);
     }
 
+    #[test]
+    fn test_count_active() {
+        let store = setup_store();
+        assert_eq!(store.count_active().unwrap(), 0);
+
+        for state in [
+            ChannelState::Opening,
+            ChannelState::Open,
+            ChannelState::Disputed,
+            ChannelState::Closed,
+        ] {
+            let peer = test_peer_id();
+            let mut channel = test_channel(peer);
+            channel.state = state;
+            store.create(&peer, channel).unwrap();
+        }
+
+        // Everything but the closed channel still locks a deposit
+        assert_eq!(store.count_active().unwrap(), 3);
+    }
+
     #[test]
     fn test_add_and_get_payments() {
-        let mut store = setup_store();
+        let store = setup_store();
         let peer = test_peer_id();
         let channel = test_channel(peer);
 
@@ -610,7 +881,7 @@ mod tests {
 
     #[test]
     fn test_clear_payments() {
-        let mut store = setup_store();
+        let store = setup_store();
         let peer = test_peer_id();
         let channel = test_channel(peer);
 
@@ -633,7 +904,7 @@ mod tests {
 
     #[test]
     fn test_channel_with_pending_payments() {
-        let mut store = setup_store();
+        let store = setup_store();
         let peer = test_peer_id();
         let channel = test_channel(peer);
 
@@ -649,7 +920,7 @@ mod tests {
 
     #[test]
     fn test_delete_channel() {
-        let mut store = setup_store();
+        let store = setup_store();
         let peer = test_peer_id();
         let channel = test_channel(peer);
 
@@ -664,7 +935,7 @@ mod tests {
 
     #[test]
     fn test_pending_close_persistence() {
-        let mut store = setup_store();
+        let store = setup_store();
         let peer = test_peer_id();
         let mut channel = test_channel(peer);
 
@@ -693,7 +964,7 @@ mod tests {
 
     #[test]
     fn test_pending_dispute_persistence() {
-        let mut store = setup_store();
+        let store = setup_store();
         let peer = test_peer_id();
         let mut channel = test_channel(peer);
 
@@ -722,7 +993,7 @@ mod tests {
 
     #[test]
     fn test_clear_pending_close() {
-        let mut store = setup_store();
+        let store = setup_store();
         let peer = test_peer_id();
         let mut channel = test_channel(peer);
 
@@ -751,7 +1022,7 @@ mod tests {
 
     #[test]
     fn test_funding_tx_id_roundtrip() {
-        let mut store = setup_store();
+        let store = setup_store();
         let peer = test_peer_id();
         let mut channel = test_channel(peer);
 
@@ -786,7 +1057,7 @@ mod tests {
 
     #[test]
     fn test_funding_tx_id_none_by_default() {
-        let mut store = setup_store();
+        let store = setup_store();
         let peer = test_peer_id();
         let channel = test_channel(peer);
 
@@ -802,4 +1073,123 @@ mod tests {
             "funding_tx_id should be None by default"
         );
     }
+
+    fn test_checkpoint(channel: &Channel, nonce: u64) -> ChannelCheckpoint {
+        ChannelCheckpoint::new(
+            channel.channel_id,
+            channel.peer_id,
+            nonce,
+            1000 - nonce * 10,
+            nonce * 10,
+            Signature::from_bytes([nonce as u8; 64]),
+            1234567890 + nonce,
+        )
+    }
+
+    #[test]
+    fn test_checkpoint_latest() {
+        let store = setup_store();
+        let peer = test_peer_id();
+        let channel = test_channel(peer);
+
+        assert!(store
+            .latest_checkpoint(&channel.channel_id)
+            .unwrap()
+            .is_none());
+
+        for nonce in [1, 3, 2] {
+            store.checkpoint(&test_checkpoint(&channel, nonce)).unwrap();
+        }
+
+        let latest = store
+            .latest_checkpoint(&channel.channel_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest, test_checkpoint(&channel, 3));
+
+        let nonces: Vec<u64> = store
+            .checkpoints(&channel.channel_id)
+            .unwrap()
+            .iter()
+            .map(|c| c.nonce)
+            .collect();
+        assert_eq!(nonces, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_checkpoint_gaps_and_prune() {
+        let store = setup_store();
+        let peer = test_peer_id();
+        let channel = test_channel(peer);
+
+        for nonce in [1, 2, 5, 6, 9] {
+            store.checkpoint(&test_checkpoint(&channel, nonce)).unwrap();
+        }
+
+        let gaps = store.checkpoint_gaps(&channel.channel_id).unwrap();
+        assert_eq!(gaps, vec![(3, 4), (7, 8)]);
+
+        let pruned = store.prune_checkpoints(&channel.channel_id, 9).unwrap();
+        assert_eq!(pruned, 4);
+        assert!(store
+            .checkpoint_gaps(&channel.channel_id)
+            .unwrap()
+            .is_empty());
+        assert_eq!(store.checkpoints(&channel.channel_id).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_delete_removes_checkpoints() {
+        let store = setup_store();
+        let peer = test_peer_id();
+        let channel = test_channel(peer);
+
+        store.create(&peer, channel.clone()).unwrap();
+        store.checkpoint(&test_checkpoint(&channel, 1)).unwrap();
+
+        store.delete(&peer).unwrap();
+        assert!(store
+            .latest_checkpoint(&channel.channel_id)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_payment_nonces_strictly_increase() {
+        let store = setup_store();
+        let peer = test_peer_id();
+        let channel = test_channel(peer);
+        let id = channel.channel_id;
+        store.create(&peer, channel).unwrap();
+
+        assert!(store.payment_nonces(&id).unwrap().is_none());
+
+        store
+            .record_payment_nonce(&id, &peer, PaymentDirection::Received, 3, 100)
+            .unwrap();
+        store
+            .record_payment_nonce(&id, &peer, PaymentDirection::Sent, 1, 101)
+            .unwrap();
+
+        // Replays and reused nonces are rejected per direction
+        for nonce in [2, 3] {
+            assert!(matches!(
+                store.record_payment_nonce(&id, &peer, PaymentDirection::Received, nonce, 102),
+                Err(StoreError::StaleNonce { last: 3, .. })
+            ));
+        }
+        store
+            .record_payment_nonce(&id, &peer, PaymentDirection::Received, 4, 103)
+            .unwrap();
+
+        let nonces = store.payment_nonces(&id).unwrap().unwrap();
+        assert_eq!(nonces.peer_id, peer);
+        assert_eq!(nonces.last(PaymentDirection::Sent), 1);
+        assert_eq!(nonces.last(PaymentDirection::Received), 4);
+        assert_eq!(nonces.highest(), 4);
+        assert_eq!(nonces.updated_at, 103);
+
+        store.delete(&peer).unwrap();
+        assert!(store.payment_nonces(&id).unwrap().is_none());
+    }
 }
@@ -6,12 +6,82 @@
 
 use std::fs::{self, File};
 use std::io::{Read, Write};
+use std::ops::Deref;
 use std::path::{Path, PathBuf};
 
+use memmap2::Mmap;
 use nodalync_crypto::{content_hash, Hash};
+use nodalync_wire::ContentBytes;
 
 use crate::error::{Result, StoreError};
-use crate::traits::ContentStore;
+use crate::traits::{ContentStore, DeltaStore};
+
+/// Blobs at least this large are memory-mapped by
+/// [`FsContentStore::load_mapped`] rather than read onto the heap.
+pub const MMAP_THRESHOLD: u64 = 256 * 1024;
+
+/// Content bytes loaded for serving.
+///
+/// Large blobs are memory-mapped, so serving them doesn't hold a heap copy
+/// and concurrent queries for the same content share the page cache.
+/// Dereferences to the content bytes either way.
+pub struct MappedContent(MappedInner);
+
+enum MappedInner {
+    Mapped(Mmap),
+    Owned(Vec<u8>),
+}
+
+impl MappedContent {
+    /// Whether the bytes are memory-mapped rather than read onto the heap.
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.0, MappedInner::Mapped(_))
+    }
+
+    /// Copy the bytes into a vector.
+    pub fn into_vec(self) -> Vec<u8> {
+        match self.0 {
+            MappedInner::Mapped(map) => map.to_vec(),
+            MappedInner::Owned(bytes) => bytes,
+        }
+    }
+}
+
+impl Deref for MappedContent {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match &self.0 {
+            MappedInner::Mapped(map) => map,
+            MappedInner::Owned(bytes) => bytes,
+        }
+    }
+}
+
+impl AsRef<[u8]> for MappedContent {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl From<MappedContent> for ContentBytes {
+    /// Shares a mapping; small blobs are moved over as they are.
+    fn from(content: MappedContent) -> Self {
+        match content.0 {
+            MappedInner::Mapped(_) => ContentBytes::shared(content),
+            MappedInner::Owned(bytes) => bytes.into(),
+        }
+    }
+}
+
+impl std::fmt::Debug for MappedContent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MappedContent")
+            .field("len", &self.len())
+            .field("mapped", &self.is_mapped())
+            .finish()
+    }
+}
 
 /// Filesystem-based content store.
 ///
@@ -24,8 +94,13 @@ use crate::traits::ContentStore;
 /// ├── ab/
 /// │   ├── abcd1234...
 /// │   └── abef5678...
-/// └── cd/
-///     └── cdef9012...
+/// ├── cd/
+/// │   └── cdef9012...
+/// ├── quarantine/
+/// │   └── {hash}          # Content that failed an integrity check
+/// └── deltas/
+///     └── {base_hash}/
+///         └── {target_hash}   # Delta from base to target
 /// ```
 pub struct FsContentStore {
     /// Root directory for content storage.
@@ -51,6 +126,41 @@ impl FsContentStore {
         self.content_dir.join(prefix).join(&hex)
     }
 
+    /// Get the directory holding quarantined content.
+    fn quarantine_dir(&self) -> PathBuf {
+        self.content_dir.join("quarantine")
+    }
+
+    /// Get the directory holding deltas from a base version.
+    fn delta_dir(&self, base: &Hash) -> PathBuf {
+        self.content_dir.join("deltas").join(format!("{}", base))
+    }
+
+    /// Load content for serving, memory-mapping blobs of at least
+    /// [`MMAP_THRESHOLD`] bytes.
+    ///
+    /// Returns `None` if the content doesn't exist.
+    pub fn load_mapped(&self, hash: &Hash) -> Result<Option<MappedContent>> {
+        let path = self.content_path(hash);
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let mut file = File::open(&path)?;
+        if file.metadata()?.len() < MMAP_THRESHOLD {
+            let mut content = Vec::new();
+            file.read_to_end(&mut content)?;
+            return Ok(Some(MappedContent(MappedInner::Owned(content))));
+        }
+
+        // SAFETY: blobs are written once under their hash and never modified
+        // in place. Deleting unlinks the file and quarantining renames it,
+        // neither of which invalidates an existing mapping.
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(Some(MappedContent(MappedInner::Mapped(map))))
+    }
+
     /// Ensure the parent directory exists for a hash.
     fn ensure_parent_dir(&self, hash: &Hash) -> Result<()> {
         let path = self.content_path(hash);
@@ -62,7 +172,7 @@ impl FsContentStore {
 }
 
 impl ContentStore for FsContentStore {
-    fn store(&mut self, content: &[u8]) -> Result<Hash> {
+    fn store(&self, content: &[u8]) -> Result<Hash> {
         let hash = content_hash(content);
 
         // If content already exists, just return the hash
@@ -82,7 +192,7 @@ impl ContentStore for FsContentStore {
         Ok(hash)
     }
 
-    fn store_verified(&mut self, hash: &Hash, content: &[u8]) -> Result<()> {
+    fn store_verified(&self, hash: &Hash, content: &[u8]) -> Result<()> {
         // Verify hash matches
         let computed = content_hash(content);
         if computed != *hash {
@@ -127,7 +237,7 @@ impl ContentStore for FsContentStore {
         self.content_path(hash).exists()
     }
 
-    fn delete(&mut self, hash: &Hash) -> Result<()> {
+    fn delete(&self, hash: &Hash) -> Result<()> {
         let path = self.content_path(hash);
 
         if path.exists() {
@@ -152,6 +262,110 @@ impl ContentStore for FsContentStore {
         let metadata = fs::metadata(&path)?;
         Ok(Some(metadata.len()))
     }
+
+    fn quarantine(&self, hash: &Hash) -> Result<()> {
+        let path = self.content_path(hash);
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let dir = self.quarantine_dir();
+        fs::create_dir_all(&dir)?;
+        fs::rename(&path, dir.join(format!("{}", hash)))?;
+
+        // Try to remove parent directory if empty (best effort)
+        if let Some(parent) = path.parent() {
+            let _ = fs::remove_dir(parent);
+        }
+
+        Ok(())
+    }
+
+    fn quarantined(&self) -> Result<Vec<Hash>> {
+        let dir = self.quarantine_dir();
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut hashes = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let name = entry?.file_name();
+            if let Some(hash) = name.to_str().and_then(parse_hex_hash) {
+                hashes.push(hash);
+            }
+        }
+        hashes.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(hashes)
+    }
+
+    fn release_quarantined(&self, hash: &Hash) -> Result<()> {
+        let path = self.quarantine_dir().join(format!("{}", hash));
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a hash from its 64-character hex file name.
+fn parse_hex_hash(name: &str) -> Option<Hash> {
+    if name.len() != 64 {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    for (byte, chunk) in bytes.iter_mut().zip(name.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok()?;
+    }
+    Some(Hash(bytes))
+}
+
+impl DeltaStore for FsContentStore {
+    fn store_delta(&self, base: &Hash, target: &Hash, delta: &[u8]) -> Result<()> {
+        let dir = self.delta_dir(base);
+        fs::create_dir_all(&dir)?;
+
+        let mut file = File::create(dir.join(format!("{}", target)))?;
+        file.write_all(delta)?;
+        file.sync_all()?;
+
+        Ok(())
+    }
+
+    fn load_delta(&self, base: &Hash, target: &Hash) -> Result<Option<Vec<u8>>> {
+        let path = self.delta_dir(base).join(format!("{}", target));
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(fs::read(&path)?))
+    }
+
+    fn delete_deltas(&self, hash: &Hash) -> Result<()> {
+        let root = self.content_dir.join("deltas");
+        if !root.exists() {
+            return Ok(());
+        }
+
+        // Deltas from this version
+        let dir = self.delta_dir(hash);
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+
+        // Deltas to this version
+        let name = format!("{}", hash);
+        for entry in fs::read_dir(&root)? {
+            let base_dir = entry?.path();
+            let path = base_dir.join(&name);
+            if path.exists() {
+                fs::remove_file(&path)?;
+                let _ = fs::remove_dir(&base_dir);
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -162,7 +376,7 @@ mod tests {
     #[test]
     fn test_store_and_load() {
         let temp_dir = TempDir::new().unwrap();
-        let mut store = FsContentStore::new(temp_dir.path()).unwrap();
+        let store = FsContentStore::new(temp_dir.path()).unwrap();
 
         let content = b"Hello, Nodalync!";
         let hash = store.store(content).unwrap();
@@ -174,7 +388,7 @@ mod tests {
     #[test]
     fn test_store_idempotent() {
         let temp_dir = TempDir::new().unwrap();
-        let mut store = FsContentStore::new(temp_dir.path()).unwrap();
+        let store = FsContentStore::new(temp_dir.path()).unwrap();
 
         let content = b"Test content";
         let hash1 = store.store(content).unwrap();
@@ -186,7 +400,7 @@ mod tests {
     #[test]
     fn test_store_verified() {
         let temp_dir = TempDir::new().unwrap();
-        let mut store = FsContentStore::new(temp_dir.path()).unwrap();
+        let store = FsContentStore::new(temp_dir.path()).unwrap();
 
         let content = b"Verified content";
         let hash = content_hash(content);
@@ -200,7 +414,7 @@ mod tests {
     #[test]
     fn test_store_verified_mismatch() {
         let temp_dir = TempDir::new().unwrap();
-        let mut store = FsContentStore::new(temp_dir.path()).unwrap();
+        let store = FsContentStore::new(temp_dir.path()).unwrap();
 
         let content = b"Some content";
         let wrong_hash = content_hash(b"Different content");
@@ -212,7 +426,7 @@ mod tests {
     #[test]
     fn test_exists() {
         let temp_dir = TempDir::new().unwrap();
-        let mut store = FsContentStore::new(temp_dir.path()).unwrap();
+        let store = FsContentStore::new(temp_dir.path()).unwrap();
 
         let content = b"Test";
         let hash = content_hash(content);
@@ -236,7 +450,7 @@ mod tests {
     #[test]
     fn test_delete() {
         let temp_dir = TempDir::new().unwrap();
-        let mut store = FsContentStore::new(temp_dir.path()).unwrap();
+        let store = FsContentStore::new(temp_dir.path()).unwrap();
 
         let content = b"To be deleted";
         let hash = store.store(content).unwrap();
@@ -249,7 +463,7 @@ mod tests {
     #[test]
     fn test_delete_nonexistent() {
         let temp_dir = TempDir::new().unwrap();
-        let mut store = FsContentStore::new(temp_dir.path()).unwrap();
+        let store = FsContentStore::new(temp_dir.path()).unwrap();
 
         let hash = content_hash(b"nonexistent");
         // Deleting nonexistent content should not error
@@ -259,7 +473,7 @@ mod tests {
     #[test]
     fn test_size() {
         let temp_dir = TempDir::new().unwrap();
-        let mut store = FsContentStore::new(temp_dir.path()).unwrap();
+        let store = FsContentStore::new(temp_dir.path()).unwrap();
 
         let content = b"Hello, World!";
         let hash = store.store(content).unwrap();
@@ -281,7 +495,7 @@ mod tests {
     #[test]
     fn test_directory_structure() {
         let temp_dir = TempDir::new().unwrap();
-        let mut store = FsContentStore::new(temp_dir.path()).unwrap();
+        let store = FsContentStore::new(temp_dir.path()).unwrap();
 
         let content = b"Test content";
         let hash = store.store(content).unwrap();
@@ -298,7 +512,7 @@ mod tests {
     #[test]
     fn test_multiple_contents_same_prefix() {
         let temp_dir = TempDir::new().unwrap();
-        let mut store = FsContentStore::new(temp_dir.path()).unwrap();
+        let store = FsContentStore::new(temp_dir.path()).unwrap();
 
         // Store multiple contents - they may or may not share prefix dirs
         let contents: Vec<&[u8]> = vec![b"content1", b"content2", b"content3"];
@@ -314,7 +528,7 @@ mod tests {
     #[test]
     fn test_large_content() {
         let temp_dir = TempDir::new().unwrap();
-        let mut store = FsContentStore::new(temp_dir.path()).unwrap();
+        let store = FsContentStore::new(temp_dir.path()).unwrap();
 
         // 1 MB of data
         let content: Vec<u8> = (0..1024 * 1024).map(|i| (i % 256) as u8).collect();
@@ -324,4 +538,81 @@ mod tests {
         assert_eq!(loaded.len(), content.len());
         assert_eq!(loaded, content);
     }
+
+    #[test]
+    fn test_load_mapped() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = FsContentStore::new(temp_dir.path()).unwrap();
+
+        let small = store.store(b"small").unwrap();
+        let loaded = store.load_mapped(&small).unwrap().unwrap();
+        assert!(!loaded.is_mapped());
+        assert_eq!(&*loaded, b"small");
+
+        let content: Vec<u8> = (0..MMAP_THRESHOLD as usize)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let large = store.store(&content).unwrap();
+        let loaded = store.load_mapped(&large).unwrap().unwrap();
+        assert!(loaded.is_mapped());
+        assert_eq!(&*loaded, content.as_slice());
+        let shared = ContentBytes::from(store.load_mapped(&large).unwrap().unwrap());
+        assert!(shared.is_shared());
+        assert_eq!(shared, content);
+
+        // The mapping outlives deletion of the blob
+        store.delete(&large).unwrap();
+        assert_eq!(loaded.into_vec(), content);
+        assert!(store.load_mapped(&large).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_delta_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = FsContentStore::new(temp_dir.path()).unwrap();
+
+        let v1 = store.store(b"version one").unwrap();
+        let v2 = store.store(b"version two").unwrap();
+        let v3 = store.store(b"version three").unwrap();
+
+        assert!(store.load_delta(&v1, &v2).unwrap().is_none());
+        store.store_delta(&v1, &v2, b"delta12").unwrap();
+        store.store_delta(&v2, &v3, b"delta23").unwrap();
+        assert_eq!(
+            store.load_delta(&v1, &v2).unwrap(),
+            Some(b"delta12".to_vec())
+        );
+
+        // Deleting v2 drops deltas to and from it, but not the blobs
+        store.delete_deltas(&v2).unwrap();
+        assert!(store.load_delta(&v1, &v2).unwrap().is_none());
+        assert!(store.load_delta(&v2, &v3).unwrap().is_none());
+        assert!(store.exists(&v2));
+    }
+
+    #[test]
+    fn test_quarantine() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = FsContentStore::new(temp_dir.path()).unwrap();
+
+        let hash = store.store(b"soon to rot").unwrap();
+        let other = store.store(b"healthy").unwrap();
+        store.quarantine(&hash).unwrap();
+
+        // Quarantined content is no longer served
+        assert!(!store.exists(&hash));
+        assert!(store.load(&hash).unwrap().is_none());
+        assert!(store.exists(&other));
+        assert_eq!(store.quarantined().unwrap(), vec![hash]);
+
+        // A good copy can be stored again, then the quarantined one released
+        store.store_verified(&hash, b"soon to rot").unwrap();
+        store.release_quarantined(&hash).unwrap();
+        assert!(store.exists(&hash));
+        assert!(store.quarantined().unwrap().is_empty());
+
+        // Quarantining missing content is a no-op
+        store.quarantine(&content_hash(b"missing")).unwrap();
+        assert!(store.quarantined().unwrap().is_empty());
+    }
 }
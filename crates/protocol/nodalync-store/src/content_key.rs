@@ -0,0 +1,132 @@
+//! Content encryption key storage.
+//!
+//! Restricted content is delivered encrypted under a per-content key. The
+//! key is created the first time the content is served and reused after
+//! that, so every authorized peer receives the same key, wrapped to their
+//! own identity.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{ContentKey, Hash, Timestamp};
+
+use crate::error::{Result, StoreError};
+use crate::traits::ContentKeyStore;
+
+/// SQLite-based content key store.
+pub struct SqliteContentKeyStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteContentKeyStore {
+    /// Create a new content key store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+
+    /// Decode a stored key.
+    fn decode(bytes: Vec<u8>) -> Result<ContentKey> {
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| StoreError::invalid_data("invalid content key length"))?;
+        Ok(ContentKey::from_bytes(bytes))
+    }
+}
+
+impl ContentKeyStore for SqliteContentKeyStore {
+    fn get(&self, hash: &Hash) -> Result<Option<ContentKey>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let key: Option<Vec<u8>> = conn
+            .query_row(
+                "SELECT key FROM content_keys WHERE hash = ?1",
+                [hash.0.to_vec()],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        key.map(Self::decode).transpose()
+    }
+
+    fn get_or_create(&self, hash: &Hash, created_at: Timestamp) -> Result<ContentKey> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        // Keep the existing key if another writer got there first
+        conn.execute(
+            "INSERT OR IGNORE INTO content_keys (hash, key, created_at) VALUES (?1, ?2, ?3)",
+            params![
+                hash.0.to_vec(),
+                ContentKey::generate().as_bytes().to_vec(),
+                created_at as i64,
+            ],
+        )?;
+
+        let key: Vec<u8> = conn.query_row(
+            "SELECT key FROM content_keys WHERE hash = ?1",
+            [hash.0.to_vec()],
+            |row| row.get(0),
+        )?;
+        Self::decode(key)
+    }
+
+    fn delete(&self, hash: &Hash) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let deleted = conn.execute(
+            "DELETE FROM content_keys WHERE hash = ?1",
+            [hash.0.to_vec()],
+        )?;
+        Ok(deleted > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::content_hash;
+
+    fn setup_store() -> SqliteContentKeyStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteContentKeyStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    #[test]
+    fn test_get_or_create_is_stable() {
+        let store = setup_store();
+        let hash = content_hash(b"restricted");
+
+        assert!(store.get(&hash).unwrap().is_none());
+        let key = store.get_or_create(&hash, 100).unwrap();
+        assert_eq!(store.get_or_create(&hash, 200).unwrap(), key);
+        assert_eq!(store.get(&hash).unwrap(), Some(key.clone()));
+
+        // Each content gets its own key
+        let other = store.get_or_create(&content_hash(b"other"), 100).unwrap();
+        assert_ne!(other, key);
+    }
+
+    #[test]
+    fn test_delete() {
+        let store = setup_store();
+        let hash = content_hash(b"restricted");
+        let key = store.get_or_create(&hash, 100).unwrap();
+
+        assert!(store.delete(&hash).unwrap());
+        assert!(!store.delete(&hash).unwrap());
+        assert!(store.get(&hash).unwrap().is_none());
+
+        // A new key is created after deletion
+        assert_ne!(store.get_or_create(&hash, 200).unwrap(), key);
+    }
+}
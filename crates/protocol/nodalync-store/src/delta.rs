@@ -0,0 +1,128 @@
+//! Delta encoding between content versions.
+//!
+//! A delta is the new version compressed with zstd, using the previous
+//! version as a raw dictionary. Unchanged regions become back-references
+//! into the previous version, so a small edit to a large document produces
+//! a small delta. Deltas are stored next to the full blobs (see
+//! [`DeltaStore`](crate::DeltaStore)); full blobs are always kept.
+
+use zstd::bulk::{Compressor, Decompressor};
+use zstd::stream::raw::{CParameter, DParameter};
+
+use crate::error::{Result, StoreError};
+
+/// zstd compression level used for deltas.
+pub const DELTA_COMPRESSION_LEVEL: i32 = 9;
+
+/// Smallest zstd window (log2 bytes).
+const MIN_WINDOW_LOG: u32 = 10;
+
+/// Largest zstd window accepted when decoding (log2 bytes).
+///
+/// 2^27 bytes covers `MAX_CONTENT_SIZE` (100 MiB).
+const MAX_WINDOW_LOG: u32 = 27;
+
+/// Encode `target` as a delta against `base`.
+///
+/// The window is sized to cover the base, so references reach back to its
+/// start even for large documents.
+pub fn encode_delta(base: &[u8], target: &[u8]) -> Result<Vec<u8>> {
+    let window_log = window_log_for(base.len().max(target.len()))?;
+
+    let mut compressor = Compressor::with_dictionary(DELTA_COMPRESSION_LEVEL, base)
+        .map_err(|e| StoreError::Delta(e.to_string()))?;
+    compressor
+        .set_parameter(CParameter::WindowLog(window_log))
+        .map_err(|e| StoreError::Delta(e.to_string()))?;
+    compressor
+        .set_parameter(CParameter::EnableLongDistanceMatching(true))
+        .map_err(|e| StoreError::Delta(e.to_string()))?;
+
+    compressor
+        .compress(target)
+        .map_err(|e| StoreError::Delta(e.to_string()))
+}
+
+/// Reconstruct content from `base` and a delta produced by [`encode_delta`].
+///
+/// `target_size` is the expected size of the result (from its manifest).
+/// The caller must still verify the result against the target hash.
+pub fn apply_delta(base: &[u8], delta: &[u8], target_size: u64) -> Result<Vec<u8>> {
+    let capacity = usize::try_from(target_size)
+        .map_err(|_| StoreError::Delta(format!("target size {} too large", target_size)))?;
+    window_log_for(capacity)?;
+
+    let mut decompressor =
+        Decompressor::with_dictionary(base).map_err(|e| StoreError::Delta(e.to_string()))?;
+    decompressor
+        .set_parameter(DParameter::WindowLogMax(MAX_WINDOW_LOG))
+        .map_err(|e| StoreError::Delta(e.to_string()))?;
+
+    let content = decompressor
+        .decompress(delta, capacity)
+        .map_err(|e| StoreError::Delta(e.to_string()))?;
+    if content.len() != capacity {
+        return Err(StoreError::Delta(format!(
+            "expected {} bytes, got {}",
+            capacity,
+            content.len()
+        )));
+    }
+    Ok(content)
+}
+
+/// Smallest window (log2) that covers `len` bytes.
+fn window_log_for(len: usize) -> Result<u32> {
+    let log = (usize::BITS - len.saturating_sub(1).leading_zeros()).max(MIN_WINDOW_LOG);
+    if log > MAX_WINDOW_LOG {
+        return Err(StoreError::Delta(format!(
+            "{} bytes exceeds the maximum delta window",
+            len
+        )));
+    }
+    Ok(log)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(paragraphs: usize) -> Vec<u8> {
+        (0..paragraphs)
+            .map(|i| format!("Paragraph {} of a long document about provenance.\n", i))
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    #[test]
+    fn test_delta_roundtrip() {
+        let base = document(2000);
+        let mut target = base.clone();
+        target.splice(50_000..50_010, b"an edit in the middle".iter().copied());
+        target.extend_from_slice(b"A new closing paragraph.\n");
+
+        let delta = encode_delta(&base, &target).unwrap();
+        assert!(delta.len() < target.len() / 20);
+
+        let rebuilt = apply_delta(&base, &delta, target.len() as u64).unwrap();
+        assert_eq!(rebuilt, target);
+    }
+
+    #[test]
+    fn test_delta_wrong_size_rejected() {
+        let base = document(10);
+        let target = document(12);
+        let delta = encode_delta(&base, &target).unwrap();
+
+        assert!(apply_delta(&base, &delta, target.len() as u64 - 1).is_err());
+        assert!(apply_delta(&base, &delta, target.len() as u64 + 1).is_err());
+    }
+
+    #[test]
+    fn test_window_log() {
+        assert_eq!(window_log_for(0).unwrap(), MIN_WINDOW_LOG);
+        assert_eq!(window_log_for(4096).unwrap(), 12);
+        assert_eq!(window_log_for(4097).unwrap(), 13);
+        assert!(window_log_for(1 << 28).is_err());
+    }
+}
@@ -0,0 +1,175 @@
+//! Activity digest storage.
+//!
+//! Digests summarize what the access log and settlement queue already
+//! hold; this store keeps the two things they don't: settlement batches
+//! that failed on-chain, and how far each digest period has been sent.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{Hash, Timestamp};
+use nodalync_types::Amount;
+
+use crate::error::{Result, StoreError};
+use crate::traits::DigestStore;
+use crate::types::{DigestPeriod, SettlementFailure};
+
+/// SQLite-based activity digest store.
+pub struct SqliteDigestStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteDigestStore {
+    /// Create a new digest store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+}
+
+impl DigestStore for SqliteDigestStore {
+    fn record_settlement_failure(&self, failure: &SettlementFailure) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        conn.execute(
+            "INSERT INTO settlement_failures (batch_id, amount, error, timestamp)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                failure.batch_id.0.to_vec(),
+                failure.amount as i64,
+                failure.error,
+                failure.timestamp as i64
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn settlement_failures(
+        &self,
+        from: Timestamp,
+        to: Timestamp,
+    ) -> Result<Vec<SettlementFailure>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT batch_id, amount, error, timestamp FROM settlement_failures
+             WHERE timestamp >= ?1 AND timestamp < ?2 ORDER BY timestamp ASC, id ASC",
+        )?;
+        let failures = stmt
+            .query_map(params![from as i64, to as i64], |row| {
+                let batch_id: Vec<u8> = row.get(0)?;
+                Ok((
+                    batch_id,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            })?
+            .filter_map(|r| r.ok())
+            .filter_map(|(batch_id, amount, error, timestamp)| {
+                let batch_id: [u8; 32] = batch_id.try_into().ok()?;
+                Some(SettlementFailure {
+                    batch_id: Hash(batch_id),
+                    amount: amount as Amount,
+                    error,
+                    timestamp: timestamp as Timestamp,
+                })
+            })
+            .collect();
+
+        Ok(failures)
+    }
+
+    fn sent_through(&self, period: DigestPeriod) -> Result<Option<Timestamp>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let through = conn
+            .query_row(
+                "SELECT sent_through FROM digest_runs WHERE period = ?1",
+                [period.as_str()],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?;
+
+        Ok(through.map(|t| t as Timestamp))
+    }
+
+    fn set_sent_through(&self, period: DigestPeriod, through: Timestamp) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        conn.execute(
+            "INSERT INTO digest_runs (period, sent_through) VALUES (?1, ?2)
+             ON CONFLICT(period) DO UPDATE SET sent_through = excluded.sent_through",
+            params![period.as_str(), through as i64],
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::content_hash;
+
+    fn setup_store() -> SqliteDigestStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteDigestStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    fn failure(timestamp: Timestamp) -> SettlementFailure {
+        SettlementFailure {
+            batch_id: content_hash(&timestamp.to_be_bytes()),
+            amount: 500,
+            error: "INSUFFICIENT_PAYER_BALANCE".to_string(),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_settlement_failures() {
+        let store = setup_store();
+        for timestamp in [3_000, 1_000, 2_000] {
+            store
+                .record_settlement_failure(&failure(timestamp))
+                .unwrap();
+        }
+
+        let failures = store.settlement_failures(1_000, 3_000).unwrap();
+        assert_eq!(failures, vec![failure(1_000), failure(2_000)]);
+        assert!(store.settlement_failures(4_000, 5_000).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sent_through() {
+        let store = setup_store();
+        assert_eq!(store.sent_through(DigestPeriod::Daily).unwrap(), None);
+
+        store.set_sent_through(DigestPeriod::Daily, 1_000).unwrap();
+        store.set_sent_through(DigestPeriod::Daily, 2_000).unwrap();
+        store.set_sent_through(DigestPeriod::Weekly, 5_000).unwrap();
+
+        assert_eq!(
+            store.sent_through(DigestPeriod::Daily).unwrap(),
+            Some(2_000)
+        );
+        assert_eq!(
+            store.sent_through(DigestPeriod::Weekly).unwrap(),
+            Some(5_000)
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! Channel dispute storage.
+//!
+//! Tracks the disputes on our channels, opened by us or by the
+//! counterparty, with their on-chain deadline, our latest counter-dispute
+//! and whether the deadline has been warned about.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{Hash, PeerId};
+
+use crate::error::{Result, StoreError};
+use crate::traits::DisputeStore;
+use crate::types::DisputeRecord;
+
+/// SQLite-based dispute store.
+pub struct SqliteDisputeStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteDisputeStore {
+    /// Create a new dispute store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+
+    /// Deserialize a dispute from a database row.
+    fn deserialize_dispute(row: &rusqlite::Row) -> rusqlite::Result<DisputeRecord> {
+        let channel_id: Vec<u8> = row.get(0)?;
+        let peer: Vec<u8> = row.get(1)?;
+
+        Ok(DisputeRecord {
+            channel_id: bytes_to_hash(&channel_id),
+            peer: bytes_to_peer_id(&peer),
+            opened_by_us: row.get(2)?,
+            started_at: row.get::<_, i64>(3)? as u64,
+            deadline: row.get::<_, i64>(4)? as u64,
+            disputed_nonce: row.get::<_, i64>(5)? as u64,
+            counter_tx_id: row.get(6)?,
+            warned_at: row.get::<_, Option<i64>>(7)?.map(|t| t as u64),
+            closed_at: row.get::<_, Option<i64>>(8)?.map(|t| t as u64),
+        })
+    }
+}
+
+impl DisputeStore for SqliteDisputeStore {
+    fn track(&self, dispute: &DisputeRecord) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO disputes
+             (channel_id, peer_id, opened_by_us, started_at, deadline, disputed_nonce,
+              counter_tx_id, warned_at, closed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                dispute.channel_id.0.to_vec(),
+                dispute.peer.0.to_vec(),
+                dispute.opened_by_us,
+                dispute.started_at as i64,
+                dispute.deadline as i64,
+                dispute.disputed_nonce as i64,
+                dispute.counter_tx_id,
+                dispute.warned_at.map(|t| t as i64),
+                dispute.closed_at.map(|t| t as i64),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn get(&self, channel_id: &Hash) -> Result<Option<DisputeRecord>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let dispute = conn
+            .prepare_cached(
+                "SELECT channel_id, peer_id, opened_by_us, started_at, deadline, disputed_nonce,
+                        counter_tx_id, warned_at, closed_at
+                 FROM disputes WHERE channel_id = ?1",
+            )?
+            .query_row([channel_id.0.to_vec()], Self::deserialize_dispute)
+            .optional()?;
+
+        Ok(dispute)
+    }
+
+    fn list(&self, include_closed: bool) -> Result<Vec<DisputeRecord>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare_cached(
+            "SELECT channel_id, peer_id, opened_by_us, started_at, deadline, disputed_nonce,
+                    counter_tx_id, warned_at, closed_at
+             FROM disputes WHERE ?1 OR closed_at IS NULL
+             ORDER BY deadline",
+        )?;
+        let disputes = stmt
+            .query_map([include_closed], Self::deserialize_dispute)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(disputes)
+    }
+}
+
+/// Convert bytes to Hash.
+fn bytes_to_hash(bytes: &[u8]) -> Hash {
+    let mut arr = [0u8; 32];
+    if bytes.len() >= 32 {
+        arr.copy_from_slice(&bytes[..32]);
+    }
+    Hash(arr)
+}
+
+/// Convert bytes to PeerId.
+fn bytes_to_peer_id(bytes: &[u8]) -> PeerId {
+    let mut arr = [0u8; 20];
+    if bytes.len() >= 20 {
+        arr.copy_from_slice(&bytes[..20]);
+    }
+    PeerId::from_bytes(arr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+
+    fn setup_store() -> SqliteDisputeStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteDisputeStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    fn dispute(name: &[u8], deadline: u64) -> DisputeRecord {
+        let (_, public_key) = generate_identity();
+        DisputeRecord {
+            channel_id: content_hash(name),
+            peer: peer_id_from_public_key(&public_key),
+            opened_by_us: false,
+            started_at: deadline - 1_000,
+            deadline,
+            disputed_nonce: 3,
+            counter_tx_id: None,
+            warned_at: None,
+            closed_at: None,
+        }
+    }
+
+    #[test]
+    fn test_track_and_update() {
+        let store = setup_store();
+        let mut tracked = dispute(b"channel", 5_000);
+        store.track(&tracked).unwrap();
+        assert_eq!(
+            store.get(&tracked.channel_id).unwrap(),
+            Some(tracked.clone())
+        );
+
+        // Countering replaces the record
+        tracked.disputed_nonce = 9;
+        tracked.counter_tx_id = Some("tx-counter".to_string());
+        tracked.warned_at = Some(4_500);
+        store.track(&tracked).unwrap();
+        assert_eq!(
+            store.get(&tracked.channel_id).unwrap(),
+            Some(tracked.clone())
+        );
+        assert!(store.get(&content_hash(b"other")).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_list_open_and_closed() {
+        let store = setup_store();
+        let late = dispute(b"late", 9_000);
+        let soon = dispute(b"soon", 5_000);
+        let closed = DisputeRecord {
+            closed_at: Some(8_000),
+            ..dispute(b"closed", 7_000)
+        };
+        for d in [&late, &soon, &closed] {
+            store.track(d).unwrap();
+        }
+
+        let open = store.list(false).unwrap();
+        assert_eq!(open, vec![soon.clone(), late.clone()]);
+        let all = store.list(true).unwrap();
+        assert_eq!(all, vec![soon, closed, late]);
+    }
+}
@@ -0,0 +1,198 @@
+//! Re-download entitlement storage.
+//!
+//! Records the paid queries we served, keyed by the receipt's payment ID,
+//! so a requester presenting that receipt later can be checked to be the
+//! peer that paid for that content, and its re-downloads counted.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{Hash, PeerId};
+use nodalync_types::Timestamp;
+
+use crate::error::{Result, StoreError};
+use crate::traits::EntitlementStore;
+use crate::types::Entitlement;
+
+/// SQLite-based entitlement store.
+pub struct SqliteEntitlementStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteEntitlementStore {
+    /// Create a new entitlement store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+
+    /// Deserialize an entitlement from a database row.
+    fn deserialize_entitlement(row: &rusqlite::Row) -> rusqlite::Result<Entitlement> {
+        let payment_id: Vec<u8> = row.get(0)?;
+        let content_hash: Vec<u8> = row.get(1)?;
+        let peer: Vec<u8> = row.get(2)?;
+
+        Ok(Entitlement {
+            payment_id: bytes_to_hash(&payment_id),
+            content_hash: bytes_to_hash(&content_hash),
+            peer: bytes_to_peer_id(&peer),
+            amount: row.get::<_, i64>(3)? as u64,
+            granted_at: row.get::<_, i64>(4)? as u64,
+            redemptions: row.get::<_, i64>(5)? as u32,
+            last_redeemed_at: row.get::<_, Option<i64>>(6)?.map(|t| t as u64),
+        })
+    }
+}
+
+impl EntitlementStore for SqliteEntitlementStore {
+    fn grant(&self, entitlement: &Entitlement) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO entitlements
+             (payment_id, content_hash, peer_id, amount, granted_at, redemptions,
+              last_redeemed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                entitlement.payment_id.0.to_vec(),
+                entitlement.content_hash.0.to_vec(),
+                entitlement.peer.0.to_vec(),
+                entitlement.amount as i64,
+                entitlement.granted_at as i64,
+                entitlement.redemptions as i64,
+                entitlement.last_redeemed_at.map(|t| t as i64),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn get(&self, payment_id: &Hash) -> Result<Option<Entitlement>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let entitlement = conn
+            .prepare_cached(
+                "SELECT payment_id, content_hash, peer_id, amount, granted_at, redemptions,
+                        last_redeemed_at
+                 FROM entitlements WHERE payment_id = ?1",
+            )?
+            .query_row([payment_id.0.to_vec()], Self::deserialize_entitlement)
+            .optional()?;
+
+        Ok(entitlement)
+    }
+
+    fn record_redemption(&self, payment_id: &Hash, at: Timestamp) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let updated = conn.execute(
+            "UPDATE entitlements
+             SET redemptions = redemptions + 1, last_redeemed_at = ?2
+             WHERE payment_id = ?1",
+            params![payment_id.0.to_vec(), at as i64],
+        )?;
+
+        Ok(updated > 0)
+    }
+
+    fn prune(&self, granted_before: Timestamp) -> Result<usize> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let removed = conn.execute(
+            "DELETE FROM entitlements WHERE granted_at < ?1",
+            [granted_before as i64],
+        )?;
+
+        Ok(removed)
+    }
+}
+
+/// Convert bytes to Hash.
+fn bytes_to_hash(bytes: &[u8]) -> Hash {
+    let mut arr = [0u8; 32];
+    if bytes.len() >= 32 {
+        arr.copy_from_slice(&bytes[..32]);
+    }
+    Hash(arr)
+}
+
+/// Convert bytes to PeerId.
+fn bytes_to_peer_id(bytes: &[u8]) -> PeerId {
+    let mut arr = [0u8; 20];
+    if bytes.len() >= 20 {
+        arr.copy_from_slice(&bytes[..20]);
+    }
+    PeerId::from_bytes(arr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+
+    fn setup_store() -> SqliteEntitlementStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteEntitlementStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    fn entitlement(granted_at: u64) -> Entitlement {
+        let (_, public_key) = generate_identity();
+        Entitlement {
+            payment_id: content_hash(&granted_at.to_be_bytes()),
+            content_hash: content_hash(b"content"),
+            peer: peer_id_from_public_key(&public_key),
+            amount: 100,
+            granted_at,
+            redemptions: 0,
+            last_redeemed_at: None,
+        }
+    }
+
+    #[test]
+    fn test_grant_and_redeem() {
+        let store = setup_store();
+        let granted = entitlement(1_000);
+        store.grant(&granted).unwrap();
+        assert_eq!(
+            store.get(&granted.payment_id).unwrap(),
+            Some(granted.clone())
+        );
+
+        assert!(store.record_redemption(&granted.payment_id, 2_000).unwrap());
+        assert!(store.record_redemption(&granted.payment_id, 3_000).unwrap());
+        let redeemed = store.get(&granted.payment_id).unwrap().unwrap();
+        assert_eq!(redeemed.redemptions, 2);
+        assert_eq!(redeemed.last_redeemed_at, Some(3_000));
+
+        // Nothing to redeem for a payment we never served
+        let unknown = content_hash(b"unknown");
+        assert!(store.get(&unknown).unwrap().is_none());
+        assert!(!store.record_redemption(&unknown, 3_000).unwrap());
+    }
+
+    #[test]
+    fn test_prune() {
+        let store = setup_store();
+        let old = entitlement(1_000);
+        let recent = entitlement(5_000);
+        store.grant(&old).unwrap();
+        store.grant(&recent).unwrap();
+
+        assert_eq!(store.prune(5_000).unwrap(), 1);
+        assert!(store.get(&old.payment_id).unwrap().is_none());
+        assert!(store.get(&recent.payment_id).unwrap().is_some());
+    }
+}
@@ -3,6 +3,7 @@
 //! This module defines the error types used throughout the nodalync-store crate.
 
 use nodalync_crypto::Hash;
+use nodalync_types::ErrorCode;
 use thiserror::Error;
 
 /// Result type alias for store operations.
@@ -59,6 +60,18 @@ pub enum StoreError {
     #[error("Cache entry not found: {0}")]
     CacheNotFound(Hash),
 
+    /// Webhook delivery not found in store.
+    #[error("Webhook delivery not found: {0}")]
+    WebhookDeliveryNotFound(u64),
+
+    /// Standing order not found in store.
+    #[error("Standing order not found: {0}")]
+    StandingOrderNotFound(u64),
+
+    /// Tenant not found in store.
+    #[error("Tenant not found: {0}")]
+    TenantNotFound(String),
+
     /// Settlement queue error.
     #[error("Settlement error: {0}")]
     Settlement(String),
@@ -67,6 +80,10 @@ pub enum StoreError {
     #[error("Schema error: {0}")]
     Schema(String),
 
+    /// Delta encoding or decoding failed.
+    #[error("Delta error: {0}")]
+    Delta(String),
+
     /// Invalid data format.
     #[error("Invalid data: {0}")]
     InvalidData(String),
@@ -78,6 +95,29 @@ pub enum StoreError {
     /// Lock poisoning error.
     #[error("lock poisoned: {0}")]
     LockPoisoned(String),
+
+    /// Another process holds the database write lock.
+    #[error("Database {path} is locked for writing by {holder}")]
+    WriteLocked { path: String, holder: String },
+
+    /// Write attempted on state opened read-only.
+    #[error("Node state is read-only: {0}")]
+    ReadOnly(String),
+
+    /// Payment nonce is not above the highest one already recorded.
+    #[error("Payment nonce {nonce} is not above last recorded nonce {last}")]
+    StaleNonce { nonce: u64, last: u64 },
+
+    /// Error from the shared Postgres database.
+    #[error("Postgres error: {0}")]
+    Postgres(String),
+}
+
+#[cfg(feature = "postgres")]
+impl From<postgres::Error> for StoreError {
+    fn from(e: postgres::Error) -> Self {
+        StoreError::Postgres(e.to_string())
+    }
 }
 
 impl StoreError {
@@ -110,6 +150,51 @@ impl StoreError {
     pub fn lock_poisoned(msg: impl Into<String>) -> Self {
         StoreError::LockPoisoned(msg.into())
     }
+
+    /// Create a Postgres error.
+    pub fn postgres(msg: impl Into<String>) -> Self {
+        StoreError::Postgres(msg.into())
+    }
+
+    /// Get the protocol error code for this error.
+    ///
+    /// Missing records map to their not-found codes and integrity failures
+    /// to theirs; database and I/O faults are internal errors.
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            Self::ContentNotFound(_)
+            | Self::ManifestNotFound(_)
+            | Self::IdentityNotFound
+            | Self::ProvenanceNotFound(_)
+            | Self::CacheNotFound(_)
+            | Self::WebhookDeliveryNotFound(_)
+            | Self::StandingOrderNotFound(_)
+            | Self::TenantNotFound(_) => ErrorCode::NotFound,
+            Self::ChannelNotFound => ErrorCode::ChannelNotFound,
+            Self::PeerNotFound => ErrorCode::PeerNotFound,
+            Self::HashMismatch { .. } => ErrorCode::InvalidHash,
+            Self::StaleNonce { .. } => ErrorCode::InvalidNonce,
+            Self::Io(_)
+            | Self::Database(_)
+            | Self::Serialization(_)
+            | Self::Encryption(_)
+            | Self::Settlement(_)
+            | Self::Schema(_)
+            | Self::Delta(_)
+            | Self::InvalidData(_)
+            | Self::Path(_)
+            | Self::LockPoisoned(_)
+            | Self::WriteLocked { .. }
+            | Self::ReadOnly(_)
+            | Self::Postgres(_) => ErrorCode::InternalError,
+        }
+    }
+}
+
+impl From<&StoreError> for ErrorCode {
+    fn from(error: &StoreError) -> Self {
+        error.error_code()
+    }
 }
 
 #[cfg(test)]
@@ -152,4 +237,21 @@ mod tests {
         let err = StoreError::schema("missing table");
         assert!(matches!(err, StoreError::Schema(_)));
     }
+
+    #[test]
+    fn test_error_code_mapping() {
+        let hash = content_hash(b"test");
+        assert_eq!(
+            StoreError::ManifestNotFound(hash).error_code(),
+            ErrorCode::NotFound
+        );
+        assert_eq!(
+            ErrorCode::from(&StoreError::StaleNonce { nonce: 1, last: 2 }),
+            ErrorCode::InvalidNonce
+        );
+        assert_eq!(
+            StoreError::schema("missing table").error_code(),
+            ErrorCode::InternalError
+        );
+    }
 }
@@ -0,0 +1,157 @@
+//! Fraud proof storage.
+//!
+//! Fraud proofs against providers that served the wrong content, whether we
+//! made them ourselves or received them from the network. Proofs are kept
+//! as the record behind a provider's lost reputation, keyed by proof hash
+//! so a proof that arrives again is only counted once.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{Hash, PeerId, Timestamp};
+use nodalync_types::FraudProof;
+
+use crate::error::{Result, StoreError};
+use crate::traits::FraudProofStore;
+
+/// SQLite-based fraud proof store.
+pub struct SqliteFraudProofStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteFraudProofStore {
+    /// Create a new fraud proof store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+}
+
+impl FraudProofStore for SqliteFraudProofStore {
+    fn store(&self, proof: &FraudProof, received_at: Timestamp) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let data = serde_json::to_string(proof)?;
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO fraud_proofs (hash, provider, data, received_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                proof.hash.0.to_vec(),
+                proof.provider.0.to_vec(),
+                data,
+                received_at as i64
+            ],
+        )?;
+
+        Ok(inserted > 0)
+    }
+
+    fn get(&self, hash: &Hash) -> Result<Option<FraudProof>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let data: Option<String> = conn
+            .query_row(
+                "SELECT data FROM fraud_proofs WHERE hash = ?1",
+                [hash.0.to_vec()],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(data.map(|data| serde_json::from_str(&data)).transpose()?)
+    }
+
+    fn list(&self, provider: Option<&PeerId>) -> Result<Vec<FraudProof>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let rows = match provider {
+            Some(provider) => conn
+                .prepare(
+                    "SELECT data FROM fraud_proofs WHERE provider = ?1
+                     ORDER BY received_at DESC",
+                )?
+                .query_map([provider.0.to_vec()], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?,
+            None => conn
+                .prepare("SELECT data FROM fraud_proofs ORDER BY received_at DESC")?
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?,
+        };
+
+        rows.iter()
+            .map(|data| Ok(serde_json::from_str(data)?))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key, Signature};
+    use nodalync_types::DeliveryCommitment;
+
+    fn setup_store() -> SqliteFraudProofStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteFraudProofStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    fn test_proof(provider: PeerId, created_at: Timestamp) -> FraudProof {
+        let (_, provider_key) = generate_identity();
+        let (_, requester_key) = generate_identity();
+        FraudProof::new(
+            content_hash(b"requested"),
+            content_hash(&created_at.to_be_bytes()),
+            provider,
+            DeliveryCommitment {
+                served_hash: content_hash(b"served"),
+                provider_key,
+                signature: Signature::from_bytes([2u8; 64]),
+            },
+            peer_id_from_public_key(&requester_key),
+            requester_key,
+            created_at,
+        )
+    }
+
+    #[test]
+    fn test_store_and_get() {
+        let store = setup_store();
+        let proof = test_proof(PeerId([1u8; 20]), 100);
+
+        assert!(store.get(&proof.hash).unwrap().is_none());
+        assert!(store.store(&proof, 100).unwrap());
+        assert_eq!(store.get(&proof.hash).unwrap(), Some(proof.clone()));
+
+        // The same proof is only stored once
+        assert!(!store.store(&proof, 200).unwrap());
+        assert_eq!(store.list(None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_list_by_provider() {
+        let store = setup_store();
+        let provider = PeerId([1u8; 20]);
+        let first = test_proof(provider, 100);
+        let second = test_proof(provider, 200);
+        let other = test_proof(PeerId([2u8; 20]), 300);
+        store.store(&first, 100).unwrap();
+        store.store(&second, 200).unwrap();
+        store.store(&other, 300).unwrap();
+
+        assert_eq!(
+            store.list(Some(&provider)).unwrap(),
+            vec![second.clone(), first.clone()]
+        );
+        assert_eq!(store.list(None).unwrap(), vec![other, second, first]);
+        assert!(store.list(Some(&PeerId([3u8; 20]))).unwrap().is_empty());
+    }
+}
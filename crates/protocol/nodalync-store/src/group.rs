@@ -0,0 +1,195 @@
+//! Group membership storage.
+//!
+//! This module stores the membership lists of groups this node owns, and
+//! cached copies of groups owned by other peers that are referenced from
+//! manifests' access control.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{Hash, Timestamp};
+use nodalync_types::Group;
+
+use crate::error::{Result, StoreError};
+use crate::traits::GroupStore;
+use crate::types::StoredGroup;
+
+/// SQLite-based group store.
+pub struct SqliteGroupStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteGroupStore {
+    /// Create a new group store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+
+    /// Decode a stored row.
+    fn decode(data: &str, fetched_at: i64) -> Result<StoredGroup> {
+        let group: Group = serde_json::from_str(data)?;
+        Ok(StoredGroup::new(group, fetched_at as Timestamp))
+    }
+}
+
+impl GroupStore for SqliteGroupStore {
+    fn store(&self, group: &Group, fetched_at: Timestamp) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let data = serde_json::to_string(group)?;
+        let changed = conn.execute(
+            "INSERT INTO groups (id, owner, name, version, data, fetched_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(id) DO UPDATE SET
+                owner = excluded.owner,
+                name = excluded.name,
+                version = excluded.version,
+                data = excluded.data,
+                fetched_at = excluded.fetched_at
+             WHERE excluded.version >= groups.version",
+            params![
+                group.id.0.to_vec(),
+                group.owner.0.to_vec(),
+                group.name,
+                group.version as i64,
+                data,
+                fetched_at as i64,
+            ],
+        )?;
+
+        Ok(changed > 0)
+    }
+
+    fn get(&self, id: &Hash) -> Result<Option<StoredGroup>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let row: Option<(String, i64)> = conn
+            .query_row(
+                "SELECT data, fetched_at FROM groups WHERE id = ?1",
+                [id.0.to_vec()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+
+        row.map(|(data, fetched_at)| Self::decode(&data, fetched_at))
+            .transpose()
+    }
+
+    fn list(&self) -> Result<Vec<StoredGroup>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare("SELECT data, fetched_at FROM groups ORDER BY name, id")?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        rows.iter()
+            .map(|(data, fetched_at)| Self::decode(data, *fetched_at))
+            .collect()
+    }
+
+    fn delete(&self, id: &Hash) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let deleted = conn.execute("DELETE FROM groups WHERE id = ?1", [id.0.to_vec()])?;
+        Ok(deleted > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+
+    fn setup_store() -> SqliteGroupStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteGroupStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    fn test_group(name: &str, version: u64) -> Group {
+        let (_, public_key) = generate_identity();
+        let member = peer_id_from_public_key(&generate_identity().1);
+        Group::new(
+            peer_id_from_public_key(&public_key),
+            public_key,
+            name,
+            vec![member],
+            version,
+            1_000,
+        )
+    }
+
+    #[test]
+    fn test_store_and_get_roundtrip() {
+        let store = setup_store();
+        let group = test_group("team", 1);
+
+        assert!(store.store(&group, 100).unwrap());
+        let stored = store.get(&group.id).unwrap().unwrap();
+        assert_eq!(stored.group, group);
+        assert_eq!(stored.fetched_at, 100);
+        assert!(store.get(&content_hash(b"missing")).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_store_keeps_highest_version() {
+        let store = setup_store();
+        let v1 = test_group("team", 1);
+        let mut v2 = v1.clone();
+        v2.version = 2;
+        v2.members.clear();
+        v2.hash = v2.compute_hash();
+
+        assert!(store.store(&v2, 100).unwrap());
+        assert!(!store.store(&v1, 200).unwrap());
+        assert_eq!(store.get(&v1.id).unwrap().unwrap().group.version, 2);
+
+        // Same version refreshes the fetch time
+        assert!(store.store(&v2, 300).unwrap());
+        assert_eq!(store.get(&v1.id).unwrap().unwrap().fetched_at, 300);
+    }
+
+    #[test]
+    fn test_list_and_delete() {
+        let store = setup_store();
+        let b = test_group("beta", 1);
+        let a = test_group("alpha", 1);
+        store.store(&b, 100).unwrap();
+        store.store(&a, 100).unwrap();
+
+        let names: Vec<_> = store
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|s| s.group.name)
+            .collect();
+        assert_eq!(names, vec!["alpha", "beta"]);
+
+        assert!(store.delete(&a.id).unwrap());
+        assert!(!store.delete(&a.id).unwrap());
+        assert_eq!(store.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_stale() {
+        let stored = StoredGroup::new(test_group("team", 1), 1_000);
+        assert!(!stored.is_stale(1_500, 1_000));
+        assert!(stored.is_stale(2_000, 1_000));
+    }
+}
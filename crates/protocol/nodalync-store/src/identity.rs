@@ -3,6 +3,9 @@
 //! This module provides encrypted storage for Ed25519 private keys.
 //! Keys are encrypted at rest using AES-256-GCM with a key derived
 //! from a user password using Argon2id.
+//!
+//! [`encrypt_with_password`] and [`decrypt_with_password`] apply the same
+//! scheme to arbitrary data, such as sync bundles moved between devices.
 
 use std::fs::{self, File};
 use std::io::{Read, Write};
@@ -13,7 +16,11 @@ use aes_gcm::{
     Aes256Gcm, Nonce,
 };
 use argon2::{password_hash::SaltString, Argon2, PasswordHasher};
-use nodalync_crypto::{generate_identity, peer_id_from_public_key, PeerId, PrivateKey, PublicKey};
+use nodalync_crypto::{
+    decrypt_content, did_from_public_key, encrypt_content, generate_identity,
+    peer_id_from_public_key, ContentKey, PeerId, PrivateKey, PublicKey,
+};
+use nodalync_types::DidDocument;
 use rand::rngs::OsRng;
 use serde::{Deserialize, Serialize};
 
@@ -22,6 +29,9 @@ use crate::error::{Result, StoreError};
 /// Nonce length for AES-GCM.
 const NONCE_LEN: usize = 12;
 
+/// Argon2 salt length for password-encrypted data.
+const SALT_LEN: usize = 16;
+
 /// Stored identity format.
 #[derive(Serialize, Deserialize)]
 struct StoredIdentity {
@@ -248,6 +258,18 @@ impl IdentityStore {
         Ok(PublicKey::from_bytes(stored.public_key))
     }
 
+    /// Get the identity's `did:key` identifier without decrypting the
+    /// private key.
+    pub fn did(&self) -> Result<String> {
+        Ok(did_from_public_key(&self.public_key()?))
+    }
+
+    /// Export the identity's DID document without decrypting the private
+    /// key.
+    pub fn did_document(&self) -> Result<DidDocument> {
+        Ok(DidDocument::new(&self.public_key()?))
+    }
+
     /// Delete the stored identity.
     ///
     /// This is irreversible and will destroy the keypair.
@@ -262,6 +284,41 @@ impl IdentityStore {
     }
 }
 
+/// Encrypt data under a password.
+///
+/// Derives an AES-256-GCM key from the password with Argon2id and a fresh
+/// random salt. Returns `salt || nonce || ciphertext`.
+pub fn encrypt_with_password(plaintext: &[u8], password: &str) -> Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    rand::Rng::fill(&mut OsRng, &mut salt);
+    let key = password_key(password, &salt)?;
+
+    let mut output = Vec::with_capacity(SALT_LEN + NONCE_LEN + plaintext.len() + 16);
+    output.extend_from_slice(&salt);
+    output.extend_from_slice(&encrypt_content(&key, plaintext));
+    Ok(output)
+}
+
+/// Decrypt data produced by [`encrypt_with_password`].
+pub fn decrypt_with_password(data: &[u8], password: &str) -> Result<Vec<u8>> {
+    if data.len() < SALT_LEN {
+        return Err(StoreError::encryption("Encrypted data too short"));
+    }
+    let (salt, ciphertext) = data.split_at(SALT_LEN);
+    let key = password_key(password, salt)?;
+    decrypt_content(&key, ciphertext)
+        .map_err(|_| StoreError::encryption("Decryption failed — wrong password or corrupted data"))
+}
+
+/// Derive an encryption key from a password and salt using Argon2id.
+fn password_key(password: &str, salt: &[u8]) -> Result<ContentKey> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| StoreError::encryption(format!("Key derivation failed: {}", e)))?;
+    Ok(ContentKey::from_bytes(key))
+}
+
 /// Base64 encode bytes.
 fn base64_encode(bytes: &[u8]) -> String {
     use std::io::Write;
@@ -478,4 +535,39 @@ mod tests {
         let (_, loaded_pk) = store.load(password).unwrap();
         assert_eq!(public_key.0, loaded_pk.0);
     }
+
+    #[test]
+    fn test_did_export() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = IdentityStore::new(temp_dir.path()).unwrap();
+        assert!(matches!(store.did(), Err(StoreError::IdentityNotFound)));
+
+        store.generate("test_password").unwrap();
+        let public_key = store.public_key().unwrap();
+
+        let did = store.did().unwrap();
+        assert_eq!(did, did_from_public_key(&public_key));
+
+        let doc = store.did_document().unwrap();
+        assert_eq!(doc.id, did);
+        assert_eq!(doc, DidDocument::new(&public_key));
+    }
+
+    #[test]
+    fn test_encrypt_with_password_roundtrip() {
+        let data = b"state to move between devices";
+        let encrypted = encrypt_with_password(data, "correct_password").unwrap();
+        assert_ne!(&encrypted[SALT_LEN + NONCE_LEN..], &data[..]);
+
+        assert_eq!(
+            decrypt_with_password(&encrypted, "correct_password").unwrap(),
+            data
+        );
+        assert!(decrypt_with_password(&encrypted, "wrong_password").is_err());
+        assert!(decrypt_with_password(&encrypted[..8], "correct_password").is_err());
+
+        // A fresh salt each time
+        let again = encrypt_with_password(data, "correct_password").unwrap();
+        assert_ne!(encrypted, again);
+    }
 }
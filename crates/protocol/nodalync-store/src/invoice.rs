@@ -0,0 +1,282 @@
+//! Invoice storage.
+//!
+//! This module tracks invoices this node issued as a payee and invoices it
+//! received as a payer, along with their payment status.
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{Hash, PeerId, PublicKey, Signature, Timestamp};
+use nodalync_types::{Amount, Invoice};
+
+use crate::error::{Result, StoreError};
+use crate::traits::InvoiceStore;
+use crate::types::{InvoiceDirection, InvoiceRecord, InvoiceStatus};
+
+/// Columns selected for an invoice record, in `row_to_record` order.
+const INVOICE_COLUMNS: &str = "hash, payee, payee_key, amount, memo, reference, created_at,
+     expires_at, signature, direction, status, payer, payment_id, paid_at";
+
+/// SQLite-based invoice store.
+pub struct SqliteInvoiceStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteInvoiceStore {
+    /// Create a new invoice store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+
+    /// Convert a database row to an InvoiceRecord.
+    ///
+    /// Returns `None` for rows with an unknown direction or status.
+    fn row_to_record(row: &Row) -> rusqlite::Result<Option<InvoiceRecord>> {
+        let hash: Vec<u8> = row.get(0)?;
+        let payee: Vec<u8> = row.get(1)?;
+        let payee_key: Vec<u8> = row.get(2)?;
+        let amount: i64 = row.get(3)?;
+        let reference: Option<Vec<u8>> = row.get(5)?;
+        let created_at: i64 = row.get(6)?;
+        let expires_at: i64 = row.get(7)?;
+        let signature: Vec<u8> = row.get(8)?;
+        let direction: String = row.get(9)?;
+        let status: String = row.get(10)?;
+        let payer: Option<Vec<u8>> = row.get(11)?;
+        let payment_id: Option<Vec<u8>> = row.get(12)?;
+        let paid_at: Option<i64> = row.get(13)?;
+
+        let (Some(direction), Some(status)) = (
+            InvoiceDirection::parse(&direction),
+            InvoiceStatus::parse(&status),
+        ) else {
+            return Ok(None);
+        };
+
+        Ok(Some(InvoiceRecord {
+            invoice: Invoice {
+                hash: Hash(fixed_bytes(&hash)),
+                payee: PeerId::from_bytes(fixed_bytes(&payee)),
+                payee_key: PublicKey::from_bytes(fixed_bytes(&payee_key)),
+                amount: amount as Amount,
+                memo: row.get(4)?,
+                reference: reference.map(|r| Hash(fixed_bytes(&r))),
+                created_at: created_at as Timestamp,
+                expires_at: expires_at as Timestamp,
+                signature: Signature::from_bytes(fixed_bytes(&signature)),
+            },
+            direction,
+            status,
+            payer: payer.map(|p| PeerId::from_bytes(fixed_bytes(&p))),
+            payment_id: payment_id.map(|p| Hash(fixed_bytes(&p))),
+            paid_at: paid_at.map(|t| t as Timestamp),
+        }))
+    }
+}
+
+impl InvoiceStore for SqliteInvoiceStore {
+    fn insert(&self, record: &InvoiceRecord) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let invoice = &record.invoice;
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO invoices
+             (hash, payee, payee_key, amount, memo, reference, created_at, expires_at, signature,
+              direction, status, payer, payment_id, paid_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            params![
+                invoice.hash.0.to_vec(),
+                invoice.payee.0.to_vec(),
+                invoice.payee_key.0.to_vec(),
+                invoice.amount as i64,
+                invoice.memo,
+                invoice.reference.map(|r| r.0.to_vec()),
+                invoice.created_at as i64,
+                invoice.expires_at as i64,
+                invoice.signature.0.to_vec(),
+                record.direction.as_str(),
+                record.status.as_str(),
+                record.payer.map(|p| p.0.to_vec()),
+                record.payment_id.map(|p| p.0.to_vec()),
+                record.paid_at.map(|t| t as i64),
+            ],
+        )?;
+
+        Ok(inserted > 0)
+    }
+
+    fn get(&self, hash: &Hash) -> Result<Option<InvoiceRecord>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let record = conn
+            .query_row(
+                &format!("SELECT {} FROM invoices WHERE hash = ?1", INVOICE_COLUMNS),
+                [hash.0.to_vec()],
+                Self::row_to_record,
+            )
+            .optional()?;
+
+        Ok(record.flatten())
+    }
+
+    fn list(
+        &self,
+        direction: Option<InvoiceDirection>,
+        status: Option<InvoiceStatus>,
+    ) -> Result<Vec<InvoiceRecord>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM invoices
+             WHERE (?1 IS NULL OR direction = ?1) AND (?2 IS NULL OR status = ?2)
+             ORDER BY created_at DESC, hash",
+            INVOICE_COLUMNS
+        ))?;
+
+        let records = stmt
+            .query_map(
+                params![direction.map(|d| d.as_str()), status.map(|s| s.as_str())],
+                Self::row_to_record,
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(records.into_iter().flatten().collect())
+    }
+
+    fn mark_paid(
+        &self,
+        hash: &Hash,
+        payer: &PeerId,
+        payment_id: &Hash,
+        paid_at: Timestamp,
+    ) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let updated = conn.execute(
+            "UPDATE invoices SET status = ?1, payer = ?2, payment_id = ?3, paid_at = ?4
+             WHERE hash = ?5 AND status = ?6",
+            params![
+                InvoiceStatus::Paid.as_str(),
+                payer.0.to_vec(),
+                payment_id.0.to_vec(),
+                paid_at as i64,
+                hash.0.to_vec(),
+                InvoiceStatus::Unpaid.as_str(),
+            ],
+        )?;
+
+        Ok(updated > 0)
+    }
+}
+
+/// Copy bytes into a fixed-size array, zero-filled if too short.
+fn fixed_bytes<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    let mut arr = [0u8; N];
+    if bytes.len() >= N {
+        arr.copy_from_slice(&bytes[..N]);
+    }
+    arr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+
+    fn setup_store() -> SqliteInvoiceStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteInvoiceStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    fn test_invoice(memo: &str, created_at: Timestamp) -> Invoice {
+        let (_, public_key) = generate_identity();
+        let mut invoice = Invoice::new(
+            peer_id_from_public_key(&public_key),
+            public_key,
+            300,
+            memo,
+            Some(content_hash(memo.as_bytes())),
+            created_at,
+            created_at + 1_000,
+        );
+        invoice.signature = Signature::from_bytes([7u8; 64]);
+        invoice
+    }
+
+    #[test]
+    fn test_insert_and_get_roundtrip() {
+        let store = setup_store();
+        let record = InvoiceRecord::new(test_invoice("dataset", 100), InvoiceDirection::Issued);
+
+        assert!(store.insert(&record).unwrap());
+        assert!(!store.insert(&record).unwrap());
+        assert_eq!(
+            store.get(&record.invoice.hash).unwrap(),
+            Some(record.clone())
+        );
+        assert!(store.get(&content_hash(b"missing")).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_mark_paid_once() {
+        let store = setup_store();
+        let record = InvoiceRecord::new(test_invoice("dataset", 100), InvoiceDirection::Issued);
+        let hash = record.invoice.hash;
+        store.insert(&record).unwrap();
+
+        let (_, payer_key) = generate_identity();
+        let payer = peer_id_from_public_key(&payer_key);
+        let payment_id = content_hash(b"payment");
+        assert!(store.mark_paid(&hash, &payer, &payment_id, 500).unwrap());
+        assert!(!store.mark_paid(&hash, &payer, &payment_id, 600).unwrap());
+
+        let paid = store.get(&hash).unwrap().unwrap();
+        assert_eq!(paid.status, InvoiceStatus::Paid);
+        assert_eq!(paid.payer, Some(payer));
+        assert_eq!(paid.payment_id, Some(payment_id));
+        assert_eq!(paid.paid_at, Some(500));
+        assert!(!paid.is_expired(10_000));
+
+        // Re-delivering the invoice doesn't reset its status
+        assert!(!store.insert(&record).unwrap());
+        assert_eq!(
+            store.get(&hash).unwrap().unwrap().status,
+            InvoiceStatus::Paid
+        );
+    }
+
+    #[test]
+    fn test_list_filters_newest_first() {
+        let store = setup_store();
+        let issued = InvoiceRecord::new(test_invoice("issued", 100), InvoiceDirection::Issued);
+        let received =
+            InvoiceRecord::new(test_invoice("received", 200), InvoiceDirection::Received);
+        store.insert(&issued).unwrap();
+        store.insert(&received).unwrap();
+
+        let all = store.list(None, None).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].invoice.memo, "received");
+
+        let issued_only = store.list(Some(InvoiceDirection::Issued), None).unwrap();
+        assert_eq!(issued_only, vec![issued]);
+        assert!(store
+            .list(None, Some(InvoiceStatus::Paid))
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,255 @@
+//! Economic ledger storage.
+//!
+//! This module records every economic event as a double-entry transaction.
+//! Each posting is stored as a row carrying the account's running balance,
+//! so balances can be read without summing the whole history.
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::Timestamp;
+use nodalync_types::Amount;
+
+use crate::error::{Result, StoreError};
+use crate::traits::LedgerStore;
+use crate::types::{LedgerAccount, LedgerEntry, LedgerEvent, LedgerTransaction};
+
+/// SQLite-based economic ledger.
+pub struct SqliteLedger {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteLedger {
+    /// Create a new ledger with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+
+    /// Get an account's balance using an open connection.
+    fn balance_in(conn: &Connection, account: LedgerAccount) -> Result<i64> {
+        let balance = conn
+            .query_row(
+                "SELECT balance FROM ledger_entries WHERE account = ?1 ORDER BY id DESC LIMIT 1",
+                [account.as_str()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(balance.unwrap_or(0))
+    }
+
+    /// Convert a database row to a LedgerEntry.
+    fn row_to_entry(row: &Row) -> rusqlite::Result<Option<LedgerEntry>> {
+        let transaction_id: i64 = row.get(0)?;
+        let event: String = row.get(1)?;
+        let timestamp: i64 = row.get(3)?;
+        let account: String = row.get(4)?;
+        let debit: i64 = row.get(5)?;
+        let credit: i64 = row.get(6)?;
+
+        let (Some(event), Some(account)) =
+            (LedgerEvent::parse(&event), LedgerAccount::parse(&account))
+        else {
+            return Ok(None);
+        };
+        Ok(Some(LedgerEntry {
+            transaction_id: transaction_id as u64,
+            event,
+            reference: row.get(2)?,
+            timestamp: timestamp as Timestamp,
+            account,
+            debit: debit as Amount,
+            credit: credit as Amount,
+            balance: row.get(7)?,
+        }))
+    }
+}
+
+impl LedgerStore for SqliteLedger {
+    fn post(&self, transaction: &LedgerTransaction) -> Result<u64> {
+        if transaction.postings.is_empty() {
+            return Err(StoreError::InvalidData(
+                "ledger transaction has no postings".to_string(),
+            ));
+        }
+        if !transaction.is_balanced() {
+            return Err(StoreError::InvalidData(format!(
+                "unbalanced {} transaction",
+                transaction.event
+            )));
+        }
+
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "INSERT INTO ledger_transactions (event, reference, timestamp) VALUES (?1, ?2, ?3)",
+            params![
+                transaction.event.as_str(),
+                transaction.reference,
+                transaction.timestamp as i64,
+            ],
+        )?;
+        let transaction_id = tx.last_insert_rowid();
+
+        for posting in &transaction.postings {
+            let change = posting.debit as i64 - posting.credit as i64;
+            let change = if posting.account.is_debit_normal() {
+                change
+            } else {
+                -change
+            };
+            let balance = Self::balance_in(&tx, posting.account)?.saturating_add(change);
+
+            tx.execute(
+                "INSERT INTO ledger_entries (transaction_id, account, debit, credit, balance)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    transaction_id,
+                    posting.account.as_str(),
+                    posting.debit as i64,
+                    posting.credit as i64,
+                    balance,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(transaction_id as u64)
+    }
+
+    fn entries(
+        &self,
+        account: Option<LedgerAccount>,
+        since: Option<Timestamp>,
+    ) -> Result<Vec<LedgerEntry>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT t.id, t.event, t.reference, t.timestamp, e.account, e.debit, e.credit, e.balance
+             FROM ledger_entries e JOIN ledger_transactions t ON t.id = e.transaction_id
+             WHERE (?1 IS NULL OR e.account = ?1) AND (?2 IS NULL OR t.timestamp >= ?2)
+             ORDER BY e.id ASC",
+        )?;
+        let entries = stmt
+            .query_map(
+                params![account.map(|a| a.as_str()), since.map(|t| t as i64)],
+                Self::row_to_entry,
+            )?
+            .filter_map(|r| r.ok().flatten())
+            .collect();
+
+        Ok(entries)
+    }
+
+    fn balance(&self, account: LedgerAccount) -> Result<i64> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+        Self::balance_in(&conn, account)
+    }
+
+    fn totals(&self) -> Result<(Amount, Amount)> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let (debits, credits): (i64, i64) = conn.query_row(
+            "SELECT COALESCE(SUM(debit), 0), COALESCE(SUM(credit), 0) FROM ledger_entries",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        Ok((debits as Amount, credits as Amount))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use crate::types::LedgerPosting;
+
+    fn create_test_ledger() -> SqliteLedger {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteLedger::new(Arc::new(Mutex::new(conn)))
+    }
+
+    #[test]
+    fn test_post_tracks_running_balances() {
+        let ledger = create_test_ledger();
+
+        ledger
+            .post(&LedgerTransaction::transfer(
+                LedgerEvent::ContractDeposit,
+                Some("0.0.1@1".to_string()),
+                1000,
+                LedgerAccount::Contract,
+                LedgerAccount::External,
+                500,
+            ))
+            .unwrap();
+        ledger
+            .post(&LedgerTransaction::transfer(
+                LedgerEvent::ChannelDeposit,
+                None,
+                2000,
+                LedgerAccount::Channels,
+                LedgerAccount::Contract,
+                200,
+            ))
+            .unwrap();
+        let revenue = LedgerTransaction::new(LedgerEvent::RevenueReceived, None, 3000)
+            .with_posting(LedgerPosting::debit(LedgerAccount::Channels, 100))
+            .with_posting(LedgerPosting::credit(LedgerAccount::Revenue, 40))
+            .with_posting(LedgerPosting::credit(LedgerAccount::Payable, 60))
+            .with_posting(LedgerPosting::credit(LedgerAccount::Fees, 0));
+        assert_eq!(revenue.postings.len(), 3);
+        ledger.post(&revenue).unwrap();
+
+        assert_eq!(ledger.balance(LedgerAccount::External).unwrap(), 500);
+        assert_eq!(ledger.balance(LedgerAccount::Contract).unwrap(), 300);
+        assert_eq!(ledger.balance(LedgerAccount::Channels).unwrap(), 300);
+        assert_eq!(ledger.balance(LedgerAccount::Payable).unwrap(), 60);
+        assert_eq!(ledger.balance(LedgerAccount::QuerySpend).unwrap(), 0);
+        assert_eq!(ledger.totals().unwrap(), (800, 800));
+
+        let channels = ledger.entries(Some(LedgerAccount::Channels), None).unwrap();
+        let balances: Vec<i64> = channels.iter().map(|e| e.balance).collect();
+        assert_eq!(balances, vec![200, 300]);
+        assert_eq!(channels[1].event, LedgerEvent::RevenueReceived);
+
+        assert_eq!(ledger.entries(None, None).unwrap().len(), 7);
+        assert_eq!(ledger.entries(None, Some(2000)).unwrap().len(), 5);
+        assert_eq!(
+            ledger.entries(None, None).unwrap()[0].reference.as_deref(),
+            Some("0.0.1@1")
+        );
+    }
+
+    #[test]
+    fn test_post_rejects_unbalanced() {
+        let ledger = create_test_ledger();
+
+        let unbalanced = LedgerTransaction::new(LedgerEvent::Fee, None, 1000)
+            .with_posting(LedgerPosting::debit(LedgerAccount::Channels, 10))
+            .with_posting(LedgerPosting::credit(LedgerAccount::Fees, 9));
+        assert!(matches!(
+            ledger.post(&unbalanced),
+            Err(StoreError::InvalidData(_))
+        ));
+
+        let empty = LedgerTransaction::new(LedgerEvent::Fee, None, 1000);
+        assert!(ledger.post(&empty).is_err());
+
+        assert!(ledger.entries(None, None).unwrap().is_empty());
+    }
+}
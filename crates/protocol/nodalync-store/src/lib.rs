@@ -2,14 +2,48 @@
 //!
 //! This crate provides persistence for all node state including:
 //!
-//! - **Content storage** (filesystem): Raw content files keyed by hash
+//! - **Content storage** (filesystem): Raw content files keyed by hash, large
+//!   blobs memory-mapped when served
+//! - **Version deltas** (filesystem): zstd deltas between content versions
 //! - **Manifest storage** (SQLite): Content metadata and economics
 //! - **Provenance graph** (SQLite): Derivation relationships for revenue distribution
-//! - **Channel storage** (SQLite): Payment channel state and pending payments
+//! - **Channel storage** (SQLite): Payment channel state, pending payments and payment nonces
 //! - **Peer storage** (SQLite): Known peer information and reputation
 //! - **Cache storage** (hybrid): Cached content from queries
 //! - **Settlement queue** (SQLite): Pending distributions awaiting batch settlement
+//! - **Access log** (SQLite): Previews and queries served, for publisher analytics
+//! - **Popularity** (SQLite): Per-hash request counters with a decaying score
+//! - **Metadata schemas** (SQLite): Cached schemas for structured metadata
+//! - **Tag registry** (SQLite): Hierarchical tags with per-tag content counts
+//! - **Economic ledger** (SQLite): Double-entry records of every economic event
+//! - **Invoices** (SQLite): Invoices issued and received, with payment status
+//! - **Groups** (SQLite): Group membership lists, owned or cached from their owners
+//! - **Content keys** (SQLite): Keys restricted content is encrypted under for delivery
+//! - **Tombstones** (SQLite): Owner-signed withdrawals of content, ours and received
+//! - **Moderation** (SQLite): Content reports received and the review queue
+//! - **Revocations** (SQLite): Revocation certificates of compromised identity keys
+//! - **Trial reads** (SQLite): Free reads of paid content served to each peer
+//! - **Quotas** (SQLite): Queries and bytes served to each peer today, per
+//!   content and node-wide
+//! - **Library** (SQLite): Content we bought, with the price paid, provider
+//!   and receipt
+//! - **Entitlements** (SQLite): Paid queries we served, which their requesters
+//!   may re-download with the receipt
+//! - **Disputes** (SQLite): Disputes on our channels, their deadlines and our
+//!   counter-disputes
+//! - **Fraud proofs** (SQLite): Proofs that providers served the wrong content
+//! - **Sync** (SQLite): Encrypted sync bundles held for other devices, channel
+//!   sync bases and receipts imported from other devices
+//! - **Replicas** (SQLite): Delegations from primaries whose catalogs we serve
+//! - **Webhooks** (SQLite): Webhook notifications and their delivery state
+//! - **Digests** (SQLite): Failed settlements and activity digests sent
+//! - **Standing orders** (SQLite): Rules for buying announced content and what
+//!   they have bought
+//! - **Availability** (SQLite): Whether our shared content is fetchable from outside
+//! - **Tenants** (SQLite): End-users served from this node, their API key hashes,
+//!   purchases and attributed content
 //! - **Identity storage** (filesystem): Encrypted private key
+//! - **Vaults** (filesystem): Independent knowledge bases with per-vault state
 //!
 //! # Storage Layout
 //!
@@ -21,8 +55,12 @@
 //! │   └── peer_id              # Public identity
 //! ├── content/
 //! │   └── {hash_prefix}/
-//! │       └── {hash}           # Raw content files
+//! │   │   └── {hash}           # Raw content files
+//! │   └── deltas/
+//! │       └── {base}/
+//! │           └── {hash}       # Delta from an earlier version
 //! ├── nodalync.db              # SQLite: manifests, provenance, channels, etc.
+//! ├── nodalync.db.lock         # Single-writer lock (holder PID + process)
 //! └── cache/
 //!     └── {hash_prefix}/
 //!         └── {hash}           # Cached content from queries
@@ -47,6 +85,22 @@
 //! assert_eq!(loaded, content);
 //! ```
 //!
+//! # Multi-Process Access
+//!
+//! Only one process may write the database at a time. [`NodeState::open`]
+//! takes an exclusive [`WriteLock`] and fails with
+//! [`StoreError::WriteLocked`] (naming the holding process) if another
+//! process already has it. Readers that can tolerate this, such as status
+//! displays, can set [`NodeStateConfig::with_read_only_fallback`] to open a
+//! read-only view instead.
+//!
+//! # Shared Stores
+//!
+//! With the `postgres` feature, [`NodeStateConfig::with_database_url`] moves
+//! manifests, peers, channels and the settlement queue to a Postgres
+//! database shared by several nodes (see [`backend`]). The rest of the state
+//! stays local.
+//!
 //! # Trait-Based Design
 //!
 //! All storage components are defined as traits, allowing for alternative
@@ -54,47 +108,132 @@
 //! implementations use filesystem and SQLite.
 
 // Module declarations
+pub mod access;
+pub mod availability;
+pub mod backend;
 pub mod cache;
 pub mod channel;
 pub mod content;
+pub mod content_key;
+pub mod delta;
+pub mod digest;
+pub mod dispute;
+pub mod entitlement;
 pub mod error;
+pub mod fraud;
+pub mod group;
 pub mod identity;
+pub mod invoice;
+pub mod ledger;
+pub mod library;
+pub mod lock;
 pub mod manifest;
+pub mod manifest_index;
+pub mod metadata_schema;
+pub mod moderation;
 pub mod peers;
+pub mod popularity;
+#[cfg(feature = "postgres")]
+pub mod postgres;
 pub mod provenance;
+pub mod quota;
+pub mod replica;
+pub mod retention;
+pub mod revocation;
 pub mod schema;
 pub mod settlement;
+pub mod standing_order;
+pub mod sync;
+pub mod tags;
+pub mod tenant;
+pub mod tombstone;
 pub mod traits;
+pub mod trial;
 pub mod types;
+pub mod vault;
+pub mod webhook;
 
 // Re-export error types
 pub use error::{Result, StoreError};
 
 // Re-export traits
 pub use traits::{
-    CacheStore, ChannelStore, ContentStore, ManifestStore, PeerStore, ProvenanceGraph,
-    SettlementQueueStore,
+    AccessLogStore, AvailabilityStore, CacheStore, ChannelStore, ContentKeyStore, ContentStore,
+    DeltaStore, DigestStore, DisputeStore, EntitlementStore, FraudProofStore, GroupStore,
+    InvoiceStore, LedgerStore, LibraryStore, ManifestStore, MetadataSchemaStore, ModerationStore,
+    PeerStore, PopularityStore, ProvenanceGraph, QuotaStore, ReplicaStore, RevocationStore,
+    SettlementQueueStore, StandingOrderStore, SyncStore, TagStore, TenantStore, TombstoneStore,
+    TrialStore, WebhookStore,
 };
 
 // Re-export types
-pub use types::{CachedContent, ManifestFilter, PeerInfo, QueuedDistribution};
+pub use types::{
+    AccessKind, AccessRecord, AccessRequester, AvailabilityCheck, AvailabilityRecord,
+    CachedContent, ChannelCheckpoint, DigestPeriod, DisputeRecord, Entitlement, InvoiceDirection,
+    InvoiceRecord, InvoiceStatus, LatencyStats, LedgerAccount, LedgerEntry, LedgerEvent,
+    LedgerPosting, LedgerTransaction, LibraryEntry, ManifestFilter, ModerationEntry,
+    ModerationStatus, PaymentDirection, PaymentNonces, PeerInfo, PopularityKind, PopularityRecord,
+    QueryReceipt, QueuedDistribution, QuotaUsage, RetentionCategory, RetentionStats,
+    SettlementFailure, StandingOrder, StandingOrderPurchase, StoredGroup, TagInfo, Tenant,
+    TenantPurchase, UsageRecord, WalletTransaction, WalletTransactionKind, WebhookDelivery,
+    WebhookDeliveryStatus, LATENCY_WINDOW, QOS_REFERENCE_LATENCY_MS,
+};
 
 // Re-export implementations
+pub use access::SqliteAccessLog;
+pub use availability::SqliteAvailabilityStore;
+pub use backend::{BatchLock, ChannelBackend, ManifestBackend, PeerBackend, SettlementBackend};
 pub use cache::FsCacheStore;
 pub use channel::SqliteChannelStore;
-pub use content::FsContentStore;
-pub use identity::IdentityStore;
+pub use content::{FsContentStore, MappedContent, MMAP_THRESHOLD};
+pub use content_key::SqliteContentKeyStore;
+pub use digest::SqliteDigestStore;
+pub use dispute::SqliteDisputeStore;
+pub use entitlement::SqliteEntitlementStore;
+pub use fraud::SqliteFraudProofStore;
+pub use group::SqliteGroupStore;
+pub use identity::{decrypt_with_password, encrypt_with_password, IdentityStore};
+pub use invoice::SqliteInvoiceStore;
+pub use ledger::SqliteLedger;
+pub use library::SqliteLibraryStore;
+pub use lock::{LockHolder, WriteLock};
 pub use manifest::SqliteManifestStore;
+pub use manifest_index::{ManifestIndexStats, DEFAULT_MANIFEST_INDEX_BYTES};
+pub use metadata_schema::SqliteMetadataSchemaStore;
+pub use moderation::SqliteModerationStore;
 pub use peers::SqlitePeerStore;
+pub use popularity::SqlitePopularityStore;
+#[cfg(feature = "postgres")]
+pub use postgres::{
+    AdvisoryLock, PgChannelStore, PgDatabase, PgManifestStore, PgPeerStore, PgSettlementQueue,
+};
 pub use provenance::SqliteProvenanceGraph;
+pub use quota::SqliteQuotaStore;
+pub use replica::SqliteReplicaStore;
+pub use revocation::SqliteRevocationStore;
 pub use settlement::SqliteSettlementQueue;
+pub use standing_order::SqliteStandingOrderStore;
+pub use sync::SqliteSyncStore;
+pub use tags::SqliteTagStore;
+pub use tenant::SqliteTenantStore;
+pub use tombstone::SqliteTombstoneStore;
+pub use trial::SqliteTrialStore;
+pub use vault::{VaultInfo, VaultManager, VaultSettings};
+pub use webhook::SqliteWebhookStore;
 
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
-use nodalync_crypto::Hash;
-use nodalync_wire::AnnouncePayload;
-use rusqlite::Connection;
+use nodalync_crypto::{Hash, PublicKey, Signature, Timestamp};
+use nodalync_wire::{AnnouncePayload, SearchFilters};
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+
+/// How long a read-only connection waits for the writer's locks.
+const READ_ONLY_BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Prepared statements kept per connection. Covers the fixed statements of
+/// every store, so hot paths never re-parse their SQL.
+const STATEMENT_CACHE_CAPACITY: usize = 128;
 
 /// Get the default data directory for Nodalync node state.
 ///
@@ -133,6 +272,16 @@ pub struct NodeStateConfig {
     pub identity_dir: Option<PathBuf>,
     /// Database file path (default: base_dir/nodalync.db).
     pub database_path: Option<PathBuf>,
+    /// Open read-only instead of failing when another process holds the
+    /// write lock (default: false).
+    pub read_only_fallback: bool,
+    /// Memory budget of the in-memory manifest index, in bytes
+    /// (default: [`DEFAULT_MANIFEST_INDEX_BYTES`]; 0 disables it).
+    pub manifest_index_bytes: usize,
+    /// Postgres database holding manifests, peers, channels and the
+    /// settlement queue, shared with other nodes (default: none, they stay
+    /// in SQLite). Needs the `postgres` feature.
+    pub database_url: Option<String>,
 }
 
 impl NodeStateConfig {
@@ -144,6 +293,9 @@ impl NodeStateConfig {
             cache_dir: None,
             identity_dir: None,
             database_path: None,
+            read_only_fallback: false,
+            manifest_index_bytes: DEFAULT_MANIFEST_INDEX_BYTES,
+            database_url: None,
         }
     }
 
@@ -171,6 +323,25 @@ impl NodeStateConfig {
         self
     }
 
+    /// Open read-only instead of failing when the database is write-locked.
+    pub fn with_read_only_fallback(mut self, enabled: bool) -> Self {
+        self.read_only_fallback = enabled;
+        self
+    }
+
+    /// Set the memory budget of the manifest index, in bytes.
+    pub fn with_manifest_index_bytes(mut self, max_bytes: usize) -> Self {
+        self.manifest_index_bytes = max_bytes;
+        self
+    }
+
+    /// Share manifests, peers, channels and the settlement queue through a
+    /// Postgres database.
+    pub fn with_database_url(mut self, url: impl Into<String>) -> Self {
+        self.database_url = Some(url.into());
+        self
+    }
+
     /// Get the content directory.
     pub fn content_dir(&self) -> PathBuf {
         self.content_dir
@@ -209,39 +380,125 @@ pub struct NodeState {
     pub identity: IdentityStore,
     /// Content storage (filesystem).
     pub content: FsContentStore,
-    /// Manifest storage (SQLite).
-    pub manifests: SqliteManifestStore,
+    /// Manifest storage (SQLite or shared Postgres).
+    pub manifests: ManifestBackend,
     /// Provenance graph (SQLite).
     pub provenance: SqliteProvenanceGraph,
-    /// Channel storage (SQLite).
-    pub channels: SqliteChannelStore,
-    /// Peer storage (SQLite).
-    pub peers: SqlitePeerStore,
+    /// Channel storage (SQLite or shared Postgres).
+    pub channels: ChannelBackend,
+    /// Peer storage (SQLite or shared Postgres).
+    pub peers: PeerBackend,
     /// Cache storage (hybrid).
     pub cache: FsCacheStore,
-    /// Settlement queue (SQLite).
-    pub settlement: SqliteSettlementQueue,
+    /// Settlement queue (SQLite or shared Postgres).
+    pub settlement: SettlementBackend,
+    /// Content access log (SQLite).
+    pub access_log: SqliteAccessLog,
+    /// Per-hash popularity counters (SQLite).
+    pub popularity: SqlitePopularityStore,
+    /// Metadata schema cache (SQLite).
+    pub schemas: SqliteMetadataSchemaStore,
+    /// Tag registry (SQLite).
+    pub tags: SqliteTagStore,
+    /// Economic ledger (SQLite).
+    pub ledger: SqliteLedger,
+    /// Issued and received invoices (SQLite).
+    pub invoices: SqliteInvoiceStore,
+    /// Group membership lists (SQLite).
+    pub groups: SqliteGroupStore,
+    /// Content encryption keys (SQLite).
+    pub content_keys: SqliteContentKeyStore,
+    /// Tombstones of withdrawn content (SQLite).
+    pub tombstones: SqliteTombstoneStore,
+    /// Content reports and moderation status (SQLite).
+    pub moderation: SqliteModerationStore,
+    /// Revocations of compromised identity keys (SQLite).
+    pub revocations: SqliteRevocationStore,
+    /// Fraud proofs against providers (SQLite).
+    pub fraud_proofs: SqliteFraudProofStore,
+    /// Free trial reads served to each peer (SQLite).
+    pub trials: SqliteTrialStore,
+    /// Daily quota usage of each peer (SQLite).
+    pub quotas: SqliteQuotaStore,
+    /// Library of content we bought (SQLite).
+    pub library: SqliteLibraryStore,
+    /// Paid queries we served, which may be re-downloaded (SQLite).
+    pub entitlements: SqliteEntitlementStore,
+    /// Disputes on our channels (SQLite).
+    pub disputes: SqliteDisputeStore,
+    /// Cross-device sync state (SQLite).
+    pub sync: SqliteSyncStore,
+    /// Delegations from primaries whose catalogs we serve (SQLite).
+    pub replicas: SqliteReplicaStore,
+    /// Webhook notifications and their delivery state (SQLite).
+    pub webhooks: SqliteWebhookStore,
+    /// Failed settlements and activity digests sent (SQLite).
+    pub digests: SqliteDigestStore,
+    /// Standing orders and their purchases (SQLite).
+    pub standing_orders: SqliteStandingOrderStore,
+    /// Availability checks of our shared content (SQLite).
+    pub availability: SqliteAvailabilityStore,
+    /// Tenants, their purchases and attributed content (SQLite).
+    pub tenants: SqliteTenantStore,
     /// Shared database connection.
     conn: Arc<Mutex<Connection>>,
     /// Configuration used to open this state.
     config: NodeStateConfig,
+    /// Write lock (None when opened read-only or in memory).
+    write_lock: Option<WriteLock>,
+    /// Process holding the write lock when opened read-only.
+    read_only_holder: Option<String>,
 }
 
 impl NodeState {
     /// Open node state with the given configuration.
     ///
-    /// Creates all necessary directories and initializes the database schema.
+    /// Creates all necessary directories, takes the database write lock, and
+    /// initializes the database schema.
+    ///
+    /// If another process holds the write lock, this fails with
+    /// [`StoreError::WriteLocked`], or opens the database read-only when
+    /// [`NodeStateConfig::read_only_fallback`] is set.
     pub fn open(config: NodeStateConfig) -> Result<Self> {
         // Create base directory
         std::fs::create_dir_all(&config.base_dir)?;
 
-        // Open database connection
         let db_path = config.database_path();
+        let (write_lock, read_only_holder) = match WriteLock::acquire(&db_path) {
+            Ok(lock) => (Some(lock), None),
+            Err(StoreError::WriteLocked { holder, .. })
+                if config.read_only_fallback && db_path.exists() =>
+            {
+                tracing::warn!(
+                    db_path = %db_path.display(),
+                    holder = %holder,
+                    "Database is locked by another process, opening read-only"
+                );
+                (None, Some(holder))
+            }
+            Err(e) => return Err(e),
+        };
+
+        // Open database connection
         tracing::info!(db_path = %db_path.display(), "Opening node state database");
-        let conn = Connection::open(&db_path)?;
+        let conn = if write_lock.is_some() {
+            let conn = Connection::open(&db_path)?;
+            // Initialize schema
+            schema::initialize_schema(&conn)?;
+            conn
+        } else {
+            let conn = Connection::open_with_flags(
+                &db_path,
+                OpenFlags::SQLITE_OPEN_READ_ONLY
+                    | OpenFlags::SQLITE_OPEN_URI
+                    | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            )?;
+            // Wait out the writer's transactions instead of failing with SQLITE_BUSY
+            conn.busy_timeout(READ_ONLY_BUSY_TIMEOUT)?;
+            conn
+        };
 
-        // Initialize schema
-        schema::initialize_schema(&conn)?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
 
         // Wrap connection in Arc<Mutex> for sharing
         let conn = Arc::new(Mutex::new(conn));
@@ -249,12 +506,34 @@ impl NodeState {
         // Create storage components
         let identity = IdentityStore::new(config.identity_dir())?;
         let content = FsContentStore::new(config.content_dir())?;
-        let manifests = SqliteManifestStore::new(Arc::clone(&conn));
+        let (manifests, channels, peers, settlement) = shared_stores(&config, &conn)?;
+        manifests.set_index_capacity(config.manifest_index_bytes);
         let provenance = SqliteProvenanceGraph::new(Arc::clone(&conn));
-        let channels = SqliteChannelStore::new(Arc::clone(&conn));
-        let peers = SqlitePeerStore::new(Arc::clone(&conn));
         let cache = FsCacheStore::new(config.cache_dir(), Arc::clone(&conn))?;
-        let settlement = SqliteSettlementQueue::new(Arc::clone(&conn));
+        let access_log = SqliteAccessLog::new(Arc::clone(&conn));
+        let popularity = SqlitePopularityStore::new(Arc::clone(&conn));
+        let schemas = SqliteMetadataSchemaStore::new(Arc::clone(&conn));
+        let tags = SqliteTagStore::new(Arc::clone(&conn));
+        let ledger = SqliteLedger::new(Arc::clone(&conn));
+        let invoices = SqliteInvoiceStore::new(Arc::clone(&conn));
+        let groups = SqliteGroupStore::new(Arc::clone(&conn));
+        let content_keys = SqliteContentKeyStore::new(Arc::clone(&conn));
+        let tombstones = SqliteTombstoneStore::new(Arc::clone(&conn));
+        let moderation = SqliteModerationStore::new(Arc::clone(&conn));
+        let revocations = SqliteRevocationStore::new(Arc::clone(&conn));
+        let fraud_proofs = SqliteFraudProofStore::new(Arc::clone(&conn));
+        let trials = SqliteTrialStore::new(Arc::clone(&conn));
+        let quotas = SqliteQuotaStore::new(Arc::clone(&conn));
+        let library = SqliteLibraryStore::new(Arc::clone(&conn));
+        let entitlements = SqliteEntitlementStore::new(Arc::clone(&conn));
+        let disputes = SqliteDisputeStore::new(Arc::clone(&conn));
+        let sync = SqliteSyncStore::new(Arc::clone(&conn));
+        let replicas = SqliteReplicaStore::new(Arc::clone(&conn));
+        let webhooks = SqliteWebhookStore::new(Arc::clone(&conn));
+        let digests = SqliteDigestStore::new(Arc::clone(&conn));
+        let standing_orders = SqliteStandingOrderStore::new(Arc::clone(&conn));
+        let availability = SqliteAvailabilityStore::new(Arc::clone(&conn));
+        let tenants = SqliteTenantStore::new(Arc::clone(&conn));
 
         Ok(Self {
             identity,
@@ -265,15 +544,41 @@ impl NodeState {
             peers,
             cache,
             settlement,
+            access_log,
+            popularity,
+            schemas,
+            tags,
+            ledger,
+            invoices,
+            groups,
+            content_keys,
+            tombstones,
+            moderation,
+            revocations,
+            fraud_proofs,
+            trials,
+            quotas,
+            library,
+            entitlements,
+            disputes,
+            sync,
+            replicas,
+            webhooks,
+            digests,
+            standing_orders,
+            availability,
+            tenants,
             conn,
             config,
+            write_lock,
+            read_only_holder,
         })
     }
 
-    /// Open node state in memory (for testing).
+    /// Open node state in memory, for tests and throwaway state such as
+    /// message replay.
     ///
     /// Uses in-memory SQLite and temporary directories.
-    #[cfg(test)]
     pub fn open_in_memory() -> Result<Self> {
         let temp_dir = std::env::temp_dir().join(format!(
             "nodalync-test-{}",
@@ -294,16 +599,41 @@ impl NodeState {
         // Open in-memory database
         let conn = Connection::open_in_memory()?;
         schema::initialize_schema(&conn)?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
         let conn = Arc::new(Mutex::new(conn));
 
         let identity = IdentityStore::new(config.identity_dir())?;
         let content = FsContentStore::new(config.content_dir())?;
-        let manifests = SqliteManifestStore::new(Arc::clone(&conn));
+        let manifests = ManifestBackend::Sqlite(SqliteManifestStore::new(Arc::clone(&conn)));
         let provenance = SqliteProvenanceGraph::new(Arc::clone(&conn));
-        let channels = SqliteChannelStore::new(Arc::clone(&conn));
-        let peers = SqlitePeerStore::new(Arc::clone(&conn));
+        let channels = ChannelBackend::Sqlite(SqliteChannelStore::new(Arc::clone(&conn)));
+        let peers = PeerBackend::Sqlite(SqlitePeerStore::new(Arc::clone(&conn)));
         let cache = FsCacheStore::new(config.cache_dir(), Arc::clone(&conn))?;
-        let settlement = SqliteSettlementQueue::new(Arc::clone(&conn));
+        let settlement = SettlementBackend::Sqlite(SqliteSettlementQueue::new(Arc::clone(&conn)));
+        let access_log = SqliteAccessLog::new(Arc::clone(&conn));
+        let popularity = SqlitePopularityStore::new(Arc::clone(&conn));
+        let schemas = SqliteMetadataSchemaStore::new(Arc::clone(&conn));
+        let tags = SqliteTagStore::new(Arc::clone(&conn));
+        let ledger = SqliteLedger::new(Arc::clone(&conn));
+        let invoices = SqliteInvoiceStore::new(Arc::clone(&conn));
+        let groups = SqliteGroupStore::new(Arc::clone(&conn));
+        let content_keys = SqliteContentKeyStore::new(Arc::clone(&conn));
+        let tombstones = SqliteTombstoneStore::new(Arc::clone(&conn));
+        let moderation = SqliteModerationStore::new(Arc::clone(&conn));
+        let revocations = SqliteRevocationStore::new(Arc::clone(&conn));
+        let fraud_proofs = SqliteFraudProofStore::new(Arc::clone(&conn));
+        let trials = SqliteTrialStore::new(Arc::clone(&conn));
+        let quotas = SqliteQuotaStore::new(Arc::clone(&conn));
+        let library = SqliteLibraryStore::new(Arc::clone(&conn));
+        let entitlements = SqliteEntitlementStore::new(Arc::clone(&conn));
+        let disputes = SqliteDisputeStore::new(Arc::clone(&conn));
+        let sync = SqliteSyncStore::new(Arc::clone(&conn));
+        let replicas = SqliteReplicaStore::new(Arc::clone(&conn));
+        let webhooks = SqliteWebhookStore::new(Arc::clone(&conn));
+        let digests = SqliteDigestStore::new(Arc::clone(&conn));
+        let standing_orders = SqliteStandingOrderStore::new(Arc::clone(&conn));
+        let availability = SqliteAvailabilityStore::new(Arc::clone(&conn));
+        let tenants = SqliteTenantStore::new(Arc::clone(&conn));
 
         Ok(Self {
             identity,
@@ -314,8 +644,34 @@ impl NodeState {
             peers,
             cache,
             settlement,
+            access_log,
+            popularity,
+            schemas,
+            tags,
+            ledger,
+            invoices,
+            groups,
+            content_keys,
+            tombstones,
+            moderation,
+            revocations,
+            fraud_proofs,
+            trials,
+            quotas,
+            library,
+            entitlements,
+            disputes,
+            sync,
+            replicas,
+            webhooks,
+            digests,
+            standing_orders,
+            availability,
+            tenants,
             conn,
             config,
+            write_lock: None,
+            read_only_holder: None,
         })
     }
 
@@ -324,6 +680,31 @@ impl NodeState {
         &self.config
     }
 
+    /// Check if this state was opened read-only because another process
+    /// holds the write lock.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only_holder.is_some()
+    }
+
+    /// Check that this state may be written.
+    ///
+    /// Returns [`StoreError::ReadOnly`] naming the lock holder otherwise, so
+    /// callers can fail early with a clear message instead of a SQLite error.
+    pub fn ensure_writable(&self) -> Result<()> {
+        match &self.read_only_holder {
+            Some(holder) => Err(StoreError::ReadOnly(format!(
+                "database is locked for writing by {}",
+                holder
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Get the write lock held by this state, if any.
+    pub fn write_lock(&self) -> Option<&WriteLock> {
+        self.write_lock.as_ref()
+    }
+
     /// Get a reference to the shared database connection.
     pub fn connection(&self) -> Arc<Mutex<Connection>> {
         Arc::clone(&self.conn)
@@ -349,29 +730,7 @@ impl NodeState {
                 return;
             }
         };
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-
-        let l1_summary_json = serde_json::to_string(&payload.l1_summary).unwrap_or_default();
-        let addresses_json = serde_json::to_string(&payload.addresses).unwrap_or_default();
-
-        // Use INSERT OR REPLACE to update existing announcements
-        if let Err(e) = conn.execute(
-            "INSERT OR REPLACE INTO announcements (hash, content_type, title, l1_summary, price, addresses, received_at, publisher_peer_id)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            rusqlite::params![
-                payload.hash.0.as_slice(),
-                payload.content_type as u8,
-                payload.title,
-                l1_summary_json,
-                payload.price as i64,
-                addresses_json,
-                now,
-                payload.publisher_peer_id,
-            ],
-        ) {
+        if let Err(e) = insert_announcement(&conn, &payload, unix_now()) {
             tracing::warn!(
                 hash = %payload.hash,
                 error = %e,
@@ -380,6 +739,57 @@ impl NodeState {
         }
     }
 
+    /// Store an announcement only if none is stored for its hash.
+    ///
+    /// For unsigned announcements made from search results, which must
+    /// never replace one received from its publisher. Returns whether it
+    /// was stored.
+    pub fn store_announcement_if_absent(&self, payload: AnnouncePayload) -> bool {
+        let conn = match self.conn.lock() {
+            Ok(c) => c,
+            Err(_) => {
+                tracing::error!("database connection lock poisoned");
+                return false;
+            }
+        };
+        let exists = conn
+            .query_row(
+                "SELECT 1 FROM announcements WHERE hash = ?1",
+                [payload.hash.0.as_slice()],
+                |_| Ok(()),
+            )
+            .optional();
+        match exists {
+            Ok(Some(())) => false,
+            Ok(None) => match insert_announcement(&conn, &payload, unix_now()) {
+                Ok(_) => true,
+                Err(e) => {
+                    tracing::warn!(hash = %payload.hash, error = %e, "Failed to store announcement");
+                    false
+                }
+            },
+            Err(e) => {
+                tracing::warn!(hash = %payload.hash, error = %e, "Failed to look up announcement");
+                false
+            }
+        }
+    }
+
+    /// Store many announcements from remote nodes in one transaction.
+    ///
+    /// Used when ingesting announcements in bulk, such as from a snapshot.
+    /// Returns the number stored; on a database error none are.
+    pub fn store_announcements(&self, payloads: &[AnnouncePayload]) -> usize {
+        store_announcement_batch(&self.conn, payloads)
+    }
+
+    /// Get a handle for storing announcements from another task.
+    pub fn announcement_writer(&self) -> AnnouncementWriter {
+        AnnouncementWriter {
+            conn: Arc::clone(&self.conn),
+        }
+    }
+
     /// Get a stored announcement by hash.
     ///
     /// Returns None if no announcement for this hash has been received.
@@ -393,37 +803,53 @@ impl NodeState {
                 return None;
             }
         };
-        conn.query_row(
-            "SELECT content_type, title, l1_summary, price, addresses, publisher_peer_id FROM announcements WHERE hash = ?1",
-            [hash.0.as_slice()],
-            |row| {
-                let content_type_u8: u8 = row.get(0)?;
-                let title: String = row.get(1)?;
-                let l1_summary_json: String = row.get(2)?;
-                let price: i64 = row.get(3)?;
-                let addresses_json: String = row.get(4)?;
-                let publisher_peer_id: Option<String> = row.get(5)?;
-
-                let content_type = ContentType::from_u8(content_type_u8).unwrap_or(ContentType::L0);
-                let l1_summary: L1Summary = serde_json::from_str(&l1_summary_json).unwrap_or_else(|_| L1Summary::empty(*hash));
-                let addresses: Vec<String> = serde_json::from_str(&addresses_json).unwrap_or_default();
-
-                Ok(AnnouncePayload {
-                    hash: *hash,
-                    content_type,
-                    title,
-                    l1_summary,
-                    price: price as u64,
-                    addresses,
-                    publisher_peer_id,
-                })
-            },
-        )
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT content_type, title, l1_summary, price, addresses, publisher_peer_id, publisher_key, signature FROM announcements WHERE hash = ?1",
+            )
+            .ok()?;
+        stmt.query_row([hash.0.as_slice()], |row| {
+            let content_type_u8: u8 = row.get(0)?;
+            let title: String = row.get(1)?;
+            let l1_summary_json: String = row.get(2)?;
+            let price: i64 = row.get(3)?;
+            let addresses_json: String = row.get(4)?;
+            let publisher_peer_id: Option<String> = row.get(5)?;
+            let publisher_key: Option<Vec<u8>> = row.get(6)?;
+            let signature: Option<Vec<u8>> = row.get(7)?;
+
+            let content_type = ContentType::from_u8(content_type_u8).unwrap_or(ContentType::L0);
+            let l1_summary: L1Summary =
+                serde_json::from_str(&l1_summary_json).unwrap_or_else(|_| L1Summary::empty(*hash));
+            let addresses: Vec<String> = serde_json::from_str(&addresses_json).unwrap_or_default();
+
+            Ok(AnnouncePayload {
+                hash: *hash,
+                content_type,
+                title,
+                l1_summary,
+                price: price as u64,
+                addresses,
+                publisher_peer_id,
+                publisher_key: publisher_key.as_deref().and_then(public_key_from_bytes),
+                signature: signature.as_deref().and_then(signature_from_bytes),
+                delegation: None,
+            })
+        })
         .ok()
     }
 
     /// List all stored announcements.
     pub fn list_announcements(&self) -> Vec<AnnouncePayload> {
+        self.list_announcements_since(0)
+    }
+
+    /// List announcements received at or after `since` (ms), newest first.
+    ///
+    /// Announcements are timestamped to the second, so this includes those
+    /// received earlier in the second `since` falls in. A re-announcement
+    /// counts as received again.
+    pub fn list_announcements_since(&self, since: Timestamp) -> Vec<AnnouncePayload> {
         use nodalync_types::{ContentType, L1Summary};
 
         let conn = match self.conn.lock() {
@@ -434,13 +860,13 @@ impl NodeState {
             }
         };
         let mut stmt = match conn.prepare(
-            "SELECT hash, content_type, title, l1_summary, price, addresses, publisher_peer_id FROM announcements ORDER BY received_at DESC",
+            "SELECT hash, content_type, title, l1_summary, price, addresses, publisher_peer_id, publisher_key, signature FROM announcements WHERE received_at >= ?1 ORDER BY received_at DESC",
         ) {
             Ok(s) => s,
             Err(_) => return Vec::new(),
         };
 
-        let rows = stmt.query_map([], |row| {
+        let rows = stmt.query_map([(since / 1000) as i64], |row| {
             let hash_bytes: Vec<u8> = row.get(0)?;
             let content_type_u8: u8 = row.get(1)?;
             let title: String = row.get(2)?;
@@ -448,6 +874,8 @@ impl NodeState {
             let price: i64 = row.get(4)?;
             let addresses_json: String = row.get(5)?;
             let publisher_peer_id: Option<String> = row.get(6)?;
+            let publisher_key: Option<Vec<u8>> = row.get(7)?;
+            let signature: Option<Vec<u8>> = row.get(8)?;
 
             let mut hash_arr = [0u8; 32];
             if hash_bytes.len() == 32 {
@@ -468,6 +896,9 @@ impl NodeState {
                 price: price as u64,
                 addresses,
                 publisher_peer_id,
+                publisher_key: publisher_key.as_deref().and_then(public_key_from_bytes),
+                signature: signature.as_deref().and_then(signature_from_bytes),
+                delegation: None,
             })
         });
 
@@ -486,9 +917,32 @@ impl NodeState {
         query: &str,
         content_type: Option<nodalync_types::ContentType>,
         limit: u32,
+    ) -> Vec<AnnouncePayload> {
+        let filters = SearchFilters {
+            content_types: content_type.map(|ct| vec![ct]),
+            ..Default::default()
+        };
+        self.search_announcements_filtered(query, &filters, limit)
+    }
+
+    /// Search stored announcements by text query and search filters.
+    ///
+    /// Content types and price are matched against the announcement. The
+    /// creation time filters are matched against when the announcement was
+    /// received. Announcements don't carry an owner or tags, so a filter on
+    /// either matches nothing.
+    pub fn search_announcements_filtered(
+        &self,
+        query: &str,
+        filters: &SearchFilters,
+        limit: u32,
     ) -> Vec<AnnouncePayload> {
         use nodalync_types::{ContentType, L1Summary};
 
+        if filters.owner.is_some() || filters.tags.is_some() {
+            return Vec::new();
+        }
+
         let conn = match self.conn.lock() {
             Ok(c) => c,
             Err(_) => {
@@ -498,32 +952,51 @@ impl NodeState {
         };
         let pattern = format!("%{}%", query.to_lowercase());
 
-        let (sql, params): (&str, Vec<Box<dyn rusqlite::ToSql>>) = if let Some(ct) = content_type {
-            (
-                "SELECT hash, content_type, title, l1_summary, price, addresses, publisher_peer_id \
-                 FROM announcements \
-                 WHERE LOWER(title) LIKE ?1 AND content_type = ?2 \
-                 ORDER BY received_at DESC LIMIT ?3",
-                vec![
-                    Box::new(pattern) as Box<dyn rusqlite::ToSql>,
-                    Box::new(ct as u8),
-                    Box::new(limit),
-                ],
-            )
-        } else {
-            (
-                "SELECT hash, content_type, title, l1_summary, price, addresses, publisher_peer_id \
-                 FROM announcements \
-                 WHERE LOWER(title) LIKE ?1 \
-                 ORDER BY received_at DESC LIMIT ?2",
-                vec![
-                    Box::new(pattern) as Box<dyn rusqlite::ToSql>,
-                    Box::new(limit),
-                ],
-            )
-        };
+        let mut sql = String::from(
+            "SELECT hash, content_type, title, l1_summary, price, addresses, publisher_peer_id, \
+             publisher_key, signature \
+             FROM announcements \
+             WHERE LOWER(title) LIKE ?1",
+        );
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(pattern)];
+
+        if let Some(ref content_types) = filters.content_types {
+            let placeholders: Vec<String> = (0..content_types.len())
+                .map(|i| format!("?{}", params.len() + i + 1))
+                .collect();
+            sql.push_str(&format!(
+                " AND content_type IN ({})",
+                placeholders.join(", ")
+            ));
+            for ct in content_types {
+                params.push(Box::new(*ct as u8));
+            }
+        }
+        if let Some(min_price) = filters.min_price {
+            sql.push_str(&format!(" AND price >= ?{}", params.len() + 1));
+            params.push(Box::new(min_price as i64));
+        }
+        if let Some(max_price) = filters.max_price {
+            sql.push_str(&format!(" AND price <= ?{}", params.len() + 1));
+            params.push(Box::new(max_price as i64));
+        }
+        // received_at is in seconds
+        if let Some(after) = filters.created_after {
+            sql.push_str(&format!(" AND received_at >= ?{}", params.len() + 1));
+            params.push(Box::new((after / 1000) as i64));
+        }
+        if let Some(before) = filters.created_before {
+            sql.push_str(&format!(" AND received_at <= ?{}", params.len() + 1));
+            params.push(Box::new((before / 1000) as i64));
+        }
 
-        let mut stmt = match conn.prepare(sql) {
+        sql.push_str(&format!(
+            " ORDER BY received_at DESC LIMIT ?{}",
+            params.len() + 1
+        ));
+        params.push(Box::new(limit));
+
+        let mut stmt = match conn.prepare(&sql) {
             Ok(s) => s,
             Err(_) => return Vec::new(),
         };
@@ -538,6 +1011,8 @@ impl NodeState {
             let price: i64 = row.get(4)?;
             let addresses_json: String = row.get(5)?;
             let publisher_peer_id: Option<String> = row.get(6)?;
+            let publisher_key: Option<Vec<u8>> = row.get(7)?;
+            let signature: Option<Vec<u8>> = row.get(8)?;
 
             let mut hash_arr = [0u8; 32];
             if hash_bytes.len() == 32 {
@@ -558,6 +1033,9 @@ impl NodeState {
                 price: price as u64,
                 addresses,
                 publisher_peer_id,
+                publisher_key: publisher_key.as_deref().and_then(public_key_from_bytes),
+                signature: signature.as_deref().and_then(signature_from_bytes),
+                delegation: None,
             })
         });
 
@@ -579,12 +1057,7 @@ impl NodeState {
                 return 0;
             }
         };
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-
-        let cutoff = now - ttl_seconds;
+        let cutoff = unix_now() - ttl_seconds;
 
         match conn.execute(
             "DELETE FROM announcements WHERE received_at < ?1",
@@ -598,6 +1071,66 @@ impl NodeState {
         }
     }
 
+    /// Remove older announcements of the same content under the same title.
+    ///
+    /// Deletes cached announcements from the same publisher (matching both
+    /// the claimed peer ID and the signing key) whose title matches
+    /// `payload`'s, ignoring case and surrounding whitespace, for any other
+    /// hash. Returns the number of announcements deleted.
+    pub fn remove_duplicate_announcements(&self, payload: &AnnouncePayload) -> u32 {
+        let conn = match self.conn.lock() {
+            Ok(c) => c,
+            Err(_) => {
+                tracing::error!("database connection lock poisoned");
+                return 0;
+            }
+        };
+
+        match conn.execute(
+            "DELETE FROM announcements
+             WHERE LOWER(TRIM(title)) = LOWER(TRIM(?1))
+                AND publisher_peer_id IS ?2
+                AND publisher_key IS ?3
+                AND hash != ?4",
+            rusqlite::params![
+                payload.title,
+                payload.publisher_peer_id,
+                payload.publisher_key.map(|k| k.0.to_vec()),
+                payload.hash.0.as_slice(),
+            ],
+        ) {
+            Ok(count) => count as u32,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to remove duplicate announcements");
+                0
+            }
+        }
+    }
+
+    /// Remove the stored announcement for a hash.
+    ///
+    /// Returns `true` if an announcement was removed.
+    pub fn remove_announcement(&self, hash: &Hash) -> bool {
+        let conn = match self.conn.lock() {
+            Ok(c) => c,
+            Err(_) => {
+                tracing::error!("database connection lock poisoned");
+                return false;
+            }
+        };
+
+        match conn.execute(
+            "DELETE FROM announcements WHERE hash = ?1",
+            rusqlite::params![hash.0.as_slice()],
+        ) {
+            Ok(count) => count > 0,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to remove announcement");
+                false
+            }
+        }
+    }
+
     /// Get the count of stored announcements.
     pub fn announcement_count(&self) -> u32 {
         let conn = match self.conn.lock() {
@@ -610,6 +1143,180 @@ impl NodeState {
         conn.query_row("SELECT COUNT(*) FROM announcements", [], |row| row.get(0))
             .unwrap_or(0)
     }
+
+    /// Count the records held in a retention category, and those last
+    /// touched before `older_than` (ms).
+    pub fn retention_stats(
+        &self,
+        category: RetentionCategory,
+        older_than: Timestamp,
+    ) -> Result<RetentionStats> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+        retention::retention_stats(&conn, category, older_than)
+    }
+
+    /// Delete the records in a retention category last touched before
+    /// `older_than` (ms).
+    ///
+    /// Peers with a channel that isn't closed, and channels that aren't
+    /// closed, are always kept. Returns the number of records deleted.
+    pub fn purge_expired(&self, category: RetentionCategory, older_than: Timestamp) -> Result<u64> {
+        self.ensure_writable()?;
+
+        if category == RetentionCategory::CachedContent {
+            let expired = {
+                let conn = self
+                    .conn
+                    .lock()
+                    .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+                retention::expired_cache_entries(&conn, older_than)?
+            };
+            let mut deleted = 0;
+            for hash in expired {
+                if self.cache.remove(&hash)? {
+                    deleted += 1;
+                }
+            }
+            return Ok(deleted);
+        }
+
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+        retention::purge_records(&mut conn, category, older_than)
+    }
+}
+
+/// Stores batches of announcements into a node's database.
+///
+/// Unlike [`NodeState`], a writer can be sent to another task, such as an
+/// ingestion task batching gossiped announcements.
+#[derive(Clone)]
+pub struct AnnouncementWriter {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl AnnouncementWriter {
+    /// Store announcements in one transaction, as
+    /// [`NodeState::store_announcements`] does.
+    pub fn store(&self, payloads: &[AnnouncePayload]) -> usize {
+        store_announcement_batch(&self.conn, payloads)
+    }
+}
+
+/// Open the stores that can be shared: in Postgres when a database URL is
+/// configured, otherwise on the node's SQLite connection.
+fn shared_stores(
+    config: &NodeStateConfig,
+    conn: &Arc<Mutex<Connection>>,
+) -> Result<(
+    ManifestBackend,
+    ChannelBackend,
+    PeerBackend,
+    SettlementBackend,
+)> {
+    if let Some(ref url) = config.database_url {
+        #[cfg(feature = "postgres")]
+        {
+            let db = PgDatabase::connect(url)?;
+            return Ok((
+                ManifestBackend::Postgres(PgManifestStore::new(db.clone())),
+                ChannelBackend::Postgres(PgChannelStore::new(db.clone())),
+                PeerBackend::Postgres(PgPeerStore::new(db.clone())),
+                SettlementBackend::Postgres(PgSettlementQueue::new(db)),
+            ));
+        }
+        #[cfg(not(feature = "postgres"))]
+        {
+            let _ = url;
+            return Err(StoreError::postgres(
+                "database_url is set but this build lacks the postgres feature",
+            ));
+        }
+    }
+
+    Ok((
+        ManifestBackend::Sqlite(SqliteManifestStore::new(Arc::clone(conn))),
+        ChannelBackend::Sqlite(SqliteChannelStore::new(Arc::clone(conn))),
+        PeerBackend::Sqlite(SqlitePeerStore::new(Arc::clone(conn))),
+        SettlementBackend::Sqlite(SqliteSettlementQueue::new(Arc::clone(conn))),
+    ))
+}
+
+/// Store announcements in one transaction, returning how many were stored.
+fn store_announcement_batch(conn: &Mutex<Connection>, payloads: &[AnnouncePayload]) -> usize {
+    let mut conn = match conn.lock() {
+        Ok(c) => c,
+        Err(_) => {
+            tracing::error!("database connection lock poisoned");
+            return 0;
+        }
+    };
+    let received_at = unix_now();
+
+    let result = conn.transaction().and_then(|tx| {
+        for payload in payloads {
+            insert_announcement(&tx, payload, received_at)?;
+        }
+        tx.commit()
+    });
+    match result {
+        Ok(()) => {
+            tracing::info!(count = payloads.len(), "Stored announcements");
+            payloads.len()
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to store announcements");
+            0
+        }
+    }
+}
+
+/// Seconds since the Unix epoch, as announcement receipt times are stored.
+fn unix_now() -> i64 {
+    (nodalync_crypto::current_timestamp() / 1000) as i64
+}
+
+/// Insert or replace an announcement received at `received_at`.
+fn insert_announcement(
+    conn: &Connection,
+    payload: &AnnouncePayload,
+    received_at: i64,
+) -> rusqlite::Result<usize> {
+    let l1_summary_json = serde_json::to_string(&payload.l1_summary).unwrap_or_default();
+    let addresses_json = serde_json::to_string(&payload.addresses).unwrap_or_default();
+
+    // Use INSERT OR REPLACE to update existing announcements
+    conn.prepare_cached(
+        "INSERT OR REPLACE INTO announcements (hash, content_type, title, l1_summary, price, addresses, received_at, publisher_peer_id, publisher_key, signature)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+    )?
+    .execute(rusqlite::params![
+        payload.hash.0.as_slice(),
+        payload.content_type as u8,
+        payload.title,
+        l1_summary_json,
+        payload.price as i64,
+        addresses_json,
+        received_at,
+        payload.publisher_peer_id,
+        payload.publisher_key.map(|k| k.0.to_vec()),
+        payload.signature.map(|s| s.0.to_vec()),
+    ])
+}
+
+/// Decode a stored publisher key, ignoring malformed bytes.
+fn public_key_from_bytes(bytes: &[u8]) -> Option<PublicKey> {
+    bytes.try_into().ok().map(PublicKey)
+}
+
+/// Decode a stored announcement signature, ignoring malformed bytes.
+fn signature_from_bytes(bytes: &[u8]) -> Option<Signature> {
+    bytes.try_into().ok().map(Signature)
 }
 
 #[cfg(test)]
@@ -628,10 +1335,51 @@ mod tests {
         assert!(state.is_ok());
     }
 
+    #[test]
+    fn test_node_state_single_writer() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = NodeStateConfig::new(temp_dir.path());
+
+        let writer = NodeState::open(config.clone()).unwrap();
+        assert!(!writer.is_read_only());
+        assert!(writer.write_lock().is_some());
+
+        // A second writer is rejected with the holder named
+        let err = NodeState::open(config.clone()).err().unwrap();
+        assert!(matches!(err, StoreError::WriteLocked { .. }));
+        assert!(err.to_string().contains(&std::process::id().to_string()));
+
+        // With fallback enabled it opens read-only and sees the writer's data
+        let hash = content_hash(b"locked content");
+        let (_, public_key) = generate_identity();
+        let manifest = Manifest::new_l0(
+            hash,
+            peer_id_from_public_key(&public_key),
+            Metadata::new("Locked", 14),
+            1234567890,
+        );
+        writer.manifests.store(&manifest).unwrap();
+
+        let reader = NodeState::open(config.clone().with_read_only_fallback(true)).unwrap();
+        assert!(reader.is_read_only());
+        assert!(matches!(
+            reader.ensure_writable(),
+            Err(StoreError::ReadOnly(_))
+        ));
+        assert!(reader.manifests.load(&hash).unwrap().is_some());
+        assert!(reader.manifests.store(&manifest).is_err());
+
+        // Once the writer closes, the lock can be taken again
+        drop(reader);
+        drop(writer);
+        let state = NodeState::open(config).unwrap();
+        assert!(state.ensure_writable().is_ok());
+    }
+
     #[test]
     fn test_node_state_content_roundtrip() {
         let state = NodeState::open_in_memory().unwrap();
-        let mut content_store = state.content;
+        let content_store = state.content;
 
         let content = b"Hello, Nodalync!";
         let hash = content_store.store(content).unwrap();
@@ -642,7 +1390,7 @@ mod tests {
 
     #[test]
     fn test_node_state_manifest_roundtrip() {
-        let mut state = NodeState::open_in_memory().unwrap();
+        let state = NodeState::open_in_memory().unwrap();
 
         let (_, public_key) = generate_identity();
         let owner = peer_id_from_public_key(&public_key);
@@ -733,6 +1481,9 @@ mod tests {
             price: 100,
             addresses: vec![],
             publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
         };
         state.store_announcement(announce1);
 
@@ -745,6 +1496,9 @@ mod tests {
             price: 200,
             addresses: vec![],
             publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
         };
         state.store_announcement(announce2);
 
@@ -757,6 +1511,9 @@ mod tests {
             price: 50,
             addresses: vec![],
             publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
         };
         state.store_announcement(announce3);
 
@@ -782,6 +1539,107 @@ mod tests {
         // Test limit
         let results = state.search_announcements("", None, 2);
         assert_eq!(results.len(), 2);
+
+        // Test price range and multiple content types
+        let filters = SearchFilters {
+            content_types: Some(vec![ContentType::L0, ContentType::L1]),
+            min_price: Some(60),
+            max_price: Some(150),
+            ..Default::default()
+        };
+        let results = state.search_announcements_filtered("", &filters, 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "Protocol Guide");
+
+        // Received just now, so nothing was received after an hour from now
+        let hour_from_now = (std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            + 3_600_000) as u64;
+        let filters = SearchFilters {
+            created_after: Some(hour_from_now),
+            ..Default::default()
+        };
+        assert!(state
+            .search_announcements_filtered("", &filters, 10)
+            .is_empty());
+
+        // Announcements carry no tags or owner
+        let filters = SearchFilters {
+            tags: Some(vec!["guide".to_string()]),
+            ..Default::default()
+        };
+        assert!(state
+            .search_announcements_filtered("", &filters, 10)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_announcement_signature_roundtrip() {
+        use nodalync_types::{ContentType, L1Summary};
+
+        let state = NodeState::open_in_memory().unwrap();
+
+        let hash = content_hash(b"signed content");
+        let announce = AnnouncePayload {
+            hash,
+            content_type: ContentType::L0,
+            title: "Signed Content".to_string(),
+            l1_summary: L1Summary::empty(hash),
+            price: 100,
+            addresses: vec!["/ip4/127.0.0.1/tcp/9000".to_string()],
+            publisher_peer_id: Some("12D3KooWPublisher".to_string()),
+            publisher_key: Some(PublicKey([7u8; 32])),
+            signature: Some(Signature([9u8; 64])),
+            delegation: None,
+        };
+        state.store_announcement(announce.clone());
+
+        assert_eq!(
+            state.get_announcement(&announce.hash),
+            Some(announce.clone())
+        );
+        assert_eq!(state.list_announcements(), vec![announce.clone()]);
+        assert_eq!(
+            state.search_announcements("signed", None, 10),
+            vec![announce]
+        );
+    }
+
+    #[test]
+    fn test_store_announcement_if_absent() {
+        use nodalync_types::{ContentType, L1Summary};
+
+        let state = NodeState::open_in_memory().unwrap();
+        let hash = content_hash(b"searched content");
+        let signed = AnnouncePayload {
+            hash,
+            content_type: ContentType::L0,
+            title: "Signed Title".to_string(),
+            l1_summary: L1Summary::empty(hash),
+            price: 100,
+            addresses: vec!["/ip4/127.0.0.1/tcp/9000".to_string()],
+            publisher_peer_id: Some("12D3KooWPublisher".to_string()),
+            publisher_key: Some(PublicKey([7u8; 32])),
+            signature: Some(Signature([9u8; 64])),
+            delegation: None,
+        };
+        let unsigned = AnnouncePayload {
+            title: "Rewritten Title".to_string(),
+            price: 1,
+            addresses: vec!["/ip4/10.0.0.1/tcp/9000".to_string()],
+            publisher_key: None,
+            signature: None,
+            ..signed.clone()
+        };
+
+        // The first announcement for a hash is stored; a later one never
+        // replaces it
+        assert!(state.store_announcement_if_absent(unsigned.clone()));
+        state.store_announcement(signed.clone());
+        assert!(!state.store_announcement_if_absent(unsigned));
+        assert_eq!(state.get_announcement(&hash), Some(signed));
     }
 
     #[test]
@@ -800,6 +1658,9 @@ mod tests {
             price: 100,
             addresses: vec![],
             publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
         };
         state.store_announcement(announce);
 
@@ -823,6 +1684,9 @@ mod tests {
             price: 50,
             addresses: vec![],
             publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
         };
         state.store_announcement(announce2);
 
@@ -833,4 +1697,127 @@ mod tests {
         // Fresh announcement should not be deleted
         assert!(count_after >= count_before - deleted);
     }
+
+    #[test]
+    fn test_list_announcements_since() {
+        use nodalync_types::{ContentType, L1Summary};
+
+        let state = NodeState::open_in_memory().unwrap();
+        let hash = content_hash(b"recent content");
+        state.store_announcement(AnnouncePayload {
+            hash,
+            content_type: ContentType::L0,
+            title: "Recent".to_string(),
+            l1_summary: L1Summary::empty(hash),
+            price: 0,
+            addresses: vec![],
+            publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
+        });
+
+        let now = unix_now() as Timestamp * 1000;
+        assert_eq!(state.list_announcements_since(0).len(), 1);
+        assert_eq!(state.list_announcements_since(now - 60_000).len(), 1);
+        assert!(state.list_announcements_since(now + 60_000).is_empty());
+    }
+
+    #[test]
+    fn test_store_announcements_batch() {
+        use nodalync_types::{ContentType, L1Summary};
+
+        let state = NodeState::open_in_memory().unwrap();
+        let payloads: Vec<_> = (0..3u8)
+            .map(|i| {
+                let hash = content_hash(&[i]);
+                AnnouncePayload {
+                    hash,
+                    content_type: ContentType::L0,
+                    title: format!("Batch {}", i),
+                    l1_summary: L1Summary::empty(hash),
+                    price: 0,
+                    addresses: vec![],
+                    publisher_peer_id: None,
+                    publisher_key: None,
+                    signature: None,
+                    delegation: None,
+                }
+            })
+            .collect();
+
+        assert_eq!(state.store_announcements(&payloads), 3);
+        assert_eq!(state.announcement_count(), 3);
+        for payload in &payloads {
+            assert_eq!(
+                state.get_announcement(&payload.hash).unwrap().title,
+                payload.title
+            );
+        }
+        assert_eq!(state.store_announcements(&[]), 0);
+    }
+
+    #[test]
+    fn test_remove_announcement() {
+        use nodalync_types::{ContentType, L1Summary};
+
+        let state = NodeState::open_in_memory().unwrap();
+        let hash = content_hash(b"withdrawn content");
+        state.store_announcement(AnnouncePayload {
+            hash,
+            content_type: ContentType::L0,
+            title: "Withdrawn".to_string(),
+            l1_summary: L1Summary::empty(hash),
+            price: 0,
+            addresses: vec![],
+            publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
+        });
+
+        assert!(state.remove_announcement(&hash));
+        assert!(state.get_announcement(&hash).is_none());
+        assert!(!state.remove_announcement(&hash));
+    }
+
+    #[test]
+    fn test_remove_duplicate_announcements() {
+        use nodalync_types::{ContentType, L1Summary};
+
+        let state = NodeState::open_in_memory().unwrap();
+        let announcement = |content: &[u8], title: &str, publisher: &str| {
+            let hash = content_hash(content);
+            AnnouncePayload {
+                hash,
+                content_type: ContentType::L0,
+                title: title.to_string(),
+                l1_summary: L1Summary::empty(hash),
+                price: 100,
+                addresses: vec![],
+                publisher_peer_id: Some(publisher.to_string()),
+                publisher_key: None,
+                signature: None,
+                delegation: None,
+            }
+        };
+
+        let first = announcement(b"v1", "Weekly Digest", "12D3KooWAlice");
+        let other_title = announcement(b"other", "Monthly Digest", "12D3KooWAlice");
+        let other_publisher = announcement(b"bob", "Weekly Digest", "12D3KooWBob");
+        for payload in [&first, &other_title, &other_publisher] {
+            state.store_announcement(payload.clone());
+        }
+
+        let second = announcement(b"v2", " weekly digest ", "12D3KooWAlice");
+        assert_eq!(state.remove_duplicate_announcements(&second), 1);
+        state.store_announcement(second.clone());
+
+        assert!(state.get_announcement(&first.hash).is_none());
+        assert!(state.get_announcement(&other_title.hash).is_some());
+        assert!(state.get_announcement(&other_publisher.hash).is_some());
+        // Re-announcing the same content doesn't remove itself
+        assert_eq!(state.remove_duplicate_announcements(&second), 0);
+        assert_eq!(state.announcement_count(), 3);
+    }
 }
@@ -0,0 +1,231 @@
+//! Purchase library storage.
+//!
+//! Records the content we bought with its title, price paid, provider and
+//! payment receipt, so our purchases can be listed, searched and re-opened
+//! from the cache. Only the latest purchase of each content hash is kept.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{Hash, PeerId};
+use nodalync_types::ContentType;
+use nodalync_wire::payload::PaymentReceipt;
+
+use crate::error::{Result, StoreError};
+use crate::traits::LibraryStore;
+use crate::types::LibraryEntry;
+
+/// SQLite-based purchase library store.
+pub struct SqliteLibraryStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteLibraryStore {
+    /// Create a new library store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+
+    /// Deserialize a library entry from a database row.
+    fn deserialize_entry(row: &rusqlite::Row) -> rusqlite::Result<LibraryEntry> {
+        let hash: Vec<u8> = row.get(0)?;
+        let content_type: u8 = row.get(2)?;
+        let provider: Vec<u8> = row.get(4)?;
+        let receipt_json: String = row.get(6)?;
+        let receipt: PaymentReceipt = serde_json::from_str(&receipt_json).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(6, rusqlite::types::Type::Text, Box::new(e))
+        })?;
+
+        Ok(LibraryEntry {
+            hash: bytes_to_hash(&hash),
+            title: row.get(1)?,
+            content_type: ContentType::from_u8(content_type).unwrap_or(ContentType::L0),
+            price_paid: row.get::<_, i64>(3)? as u64,
+            provider: bytes_to_peer_id(&provider),
+            purchased_at: row.get::<_, i64>(5)? as u64,
+            receipt,
+        })
+    }
+
+    /// Select entries matching a `WHERE` clause, most recent first.
+    fn select(&self, clause: &str, params: impl rusqlite::Params) -> Result<Vec<LibraryEntry>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT hash, title, content_type, price_paid, provider, purchased_at, receipt
+             FROM library {} ORDER BY purchased_at DESC",
+            clause
+        ))?;
+        let entries = stmt
+            .query_map(params, Self::deserialize_entry)?
+            .collect::<std::result::Result<_, _>>()?;
+
+        Ok(entries)
+    }
+}
+
+impl LibraryStore for SqliteLibraryStore {
+    fn record(&self, entry: &LibraryEntry) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let receipt_json = serde_json::to_string(&entry.receipt)?;
+        conn.execute(
+            "INSERT OR REPLACE INTO library
+             (hash, title, content_type, price_paid, provider, purchased_at, receipt)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                entry.hash.0.to_vec(),
+                entry.title,
+                entry.content_type as u8,
+                entry.price_paid as i64,
+                entry.provider.0.to_vec(),
+                entry.purchased_at as i64,
+                receipt_json,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn get(&self, hash: &Hash) -> Result<Option<LibraryEntry>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let entry = conn
+            .prepare_cached(
+                "SELECT hash, title, content_type, price_paid, provider, purchased_at, receipt
+                 FROM library WHERE hash = ?1",
+            )?
+            .query_row([hash.0.to_vec()], Self::deserialize_entry)
+            .optional()?;
+
+        Ok(entry)
+    }
+
+    fn list(&self) -> Result<Vec<LibraryEntry>> {
+        self.select("", [])
+    }
+
+    fn search(&self, text: &str) -> Result<Vec<LibraryEntry>> {
+        // Escape LIKE wildcards so the text matches literally
+        let pattern = format!(
+            "%{}%",
+            text.replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        self.select("WHERE title LIKE ?1 ESCAPE '\\'", [pattern])
+    }
+
+    fn remove(&self, hash: &Hash) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let removed = conn.execute("DELETE FROM library WHERE hash = ?1", [hash.0.to_vec()])?;
+
+        Ok(removed > 0)
+    }
+}
+
+/// Convert bytes to Hash.
+fn bytes_to_hash(bytes: &[u8]) -> Hash {
+    let mut arr = [0u8; 32];
+    if bytes.len() >= 32 {
+        arr.copy_from_slice(&bytes[..32]);
+    }
+    Hash(arr)
+}
+
+/// Convert bytes to PeerId.
+fn bytes_to_peer_id(bytes: &[u8]) -> PeerId {
+    let mut arr = [0u8; 20];
+    if bytes.len() >= 20 {
+        arr.copy_from_slice(&bytes[..20]);
+    }
+    PeerId::from_bytes(arr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key, Signature};
+
+    fn setup_store() -> SqliteLibraryStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteLibraryStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    fn entry(title: &str, price_paid: u64, purchased_at: u64) -> LibraryEntry {
+        let (_, public_key) = generate_identity();
+        let hash = content_hash(title.as_bytes());
+        LibraryEntry {
+            hash,
+            title: title.to_string(),
+            content_type: ContentType::L0,
+            price_paid,
+            provider: peer_id_from_public_key(&public_key),
+            purchased_at,
+            receipt: PaymentReceipt {
+                payment_id: content_hash(&purchased_at.to_be_bytes()),
+                amount: price_paid,
+                timestamp: purchased_at,
+                channel_nonce: 1,
+                distributor_signature: Signature::from_bytes([0u8; 64]),
+                app_fee: 0,
+                app_fee_recipient: None,
+                referral_fee: 0,
+                referrer: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_record_list_and_search() {
+        let store = setup_store();
+        let owls = entry("Owls of the North", 100, 1_000);
+        let moss = entry("Moss and Lichen", 50, 2_000);
+        let sale = entry("100% Owls_", 10, 3_000);
+        store.record(&owls).unwrap();
+        store.record(&moss).unwrap();
+        store.record(&sale).unwrap();
+
+        assert_eq!(store.get(&owls.hash).unwrap(), Some(owls.clone()));
+        let titles: Vec<String> = store.list().unwrap().into_iter().map(|e| e.title).collect();
+        assert_eq!(
+            titles,
+            ["100% Owls_", "Moss and Lichen", "Owls of the North"]
+        );
+
+        // Case-insensitive, with wildcards matched literally
+        assert_eq!(store.search("owls").unwrap().len(), 2);
+        assert_eq!(store.search("%").unwrap(), vec![sale.clone()]);
+        assert_eq!(store.search("s_").unwrap(), vec![sale]);
+        assert!(store.search("ferns").unwrap().is_empty());
+
+        // Buying again replaces the earlier purchase
+        let again = LibraryEntry {
+            price_paid: 120,
+            purchased_at: 4_000,
+            ..owls.clone()
+        };
+        store.record(&again).unwrap();
+        assert_eq!(store.list().unwrap().len(), 3);
+        assert_eq!(store.list().unwrap()[0], again);
+
+        assert!(store.remove(&owls.hash).unwrap());
+        assert!(!store.remove(&owls.hash).unwrap());
+        assert_eq!(store.get(&owls.hash).unwrap(), None);
+    }
+}
@@ -0,0 +1,172 @@
+//! Single-writer lock for the node database.
+//!
+//! Only one process may write a node's SQLite database at a time. The
+//! writer holds an exclusive advisory lock on `{database}.lock` for as long
+//! as its [`NodeState`](crate::NodeState) is open, and records who it is in
+//! the lock file so other processes can report which process holds the lock.
+//!
+//! The lock is released automatically when the holder exits, including on
+//! crashes, so there is no stale-lock cleanup.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, StoreError};
+
+/// Suffix appended to the database path to form the lock file path.
+const LOCK_FILE_SUFFIX: &str = ".lock";
+
+/// Information about the process holding the write lock.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockHolder {
+    /// Process ID.
+    pub pid: u32,
+    /// Executable name (e.g. `nodalync`).
+    pub process: String,
+    /// Unix timestamp (seconds) when the lock was acquired.
+    pub acquired_at: u64,
+}
+
+impl LockHolder {
+    /// Describe the current process.
+    fn current() -> Self {
+        let process = std::env::current_exe()
+            .ok()
+            .and_then(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "unknown".to_string());
+        Self {
+            pid: std::process::id(),
+            process,
+            acquired_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        }
+    }
+}
+
+impl std::fmt::Display for LockHolder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (PID {})", self.process, self.pid)
+    }
+}
+
+/// An exclusive write lock on a node database.
+///
+/// Released when dropped.
+#[derive(Debug)]
+pub struct WriteLock {
+    /// Open lock file; the OS lock lives as long as this handle.
+    _file: File,
+    /// Lock file path.
+    path: PathBuf,
+}
+
+impl WriteLock {
+    /// Get the lock file path for a database path.
+    pub fn lock_path(db_path: &Path) -> PathBuf {
+        let mut path = db_path.as_os_str().to_owned();
+        path.push(LOCK_FILE_SUFFIX);
+        PathBuf::from(path)
+    }
+
+    /// Acquire the write lock for a database.
+    ///
+    /// Returns [`StoreError::WriteLocked`] naming the holder if another
+    /// process (or another open state in this process) holds the lock.
+    pub fn acquire(db_path: &Path) -> Result<Self> {
+        let path = Self::lock_path(db_path);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let holder = read_holder(&mut file)
+                    .map(|h| h.to_string())
+                    .unwrap_or_else(|| "another process".to_string());
+                return Err(StoreError::WriteLocked {
+                    path: db_path.display().to_string(),
+                    holder,
+                });
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+
+        // Record ourselves as the holder
+        let holder = serde_json::to_string(&LockHolder::current())?;
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(holder.as_bytes())?;
+        file.sync_data()?;
+
+        tracing::debug!(path = %path.display(), "Acquired database write lock");
+        Ok(Self { _file: file, path })
+    }
+
+    /// Get the process currently recorded as holding the lock.
+    ///
+    /// The record is not cleared on release, so this is only meaningful
+    /// while the lock is actually held.
+    pub fn holder(db_path: &Path) -> Option<LockHolder> {
+        let mut file = File::open(Self::lock_path(db_path)).ok()?;
+        read_holder(&mut file)
+    }
+
+    /// Get the lock file path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Read the holder record from an open lock file.
+fn read_holder(file: &mut File) -> Option<LockHolder> {
+    let mut data = String::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.read_to_string(&mut data).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lock_is_exclusive_and_released_on_drop() {
+        let temp = TempDir::new().unwrap();
+        let db_path = temp.path().join("nodalync.db");
+
+        let lock = WriteLock::acquire(&db_path).unwrap();
+        assert_eq!(lock.path(), temp.path().join("nodalync.db.lock"));
+
+        let err = WriteLock::acquire(&db_path).unwrap_err();
+        match err {
+            StoreError::WriteLocked { holder, .. } => {
+                assert!(holder.contains(&std::process::id().to_string()));
+            }
+            other => panic!("expected WriteLocked, got {:?}", other),
+        }
+
+        drop(lock);
+        assert!(WriteLock::acquire(&db_path).is_ok());
+    }
+
+    #[test]
+    fn test_holder_recorded() {
+        let temp = TempDir::new().unwrap();
+        let db_path = temp.path().join("nodalync.db");
+
+        let _lock = WriteLock::acquire(&db_path).unwrap();
+        let holder = WriteLock::holder(&db_path).unwrap();
+        assert_eq!(holder.pid, std::process::id());
+        assert!(!holder.process.is_empty());
+    }
+}
@@ -1,30 +1,203 @@
 //! SQLite-based manifest storage.
 //!
 //! This module implements manifest storage using SQLite for efficient
-//! querying and filtering.
+//! querying and filtering. Recently loaded manifests are indexed in memory
+//! (see [`crate::manifest_index`]), and manifests of frequently requested
+//! content can be pinned there as well (see
+//! [`SqliteManifestStore::retain_hot`]).
 
 use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 use nodalync_crypto::{Hash, PeerId, Timestamp};
 use nodalync_types::{
-    AccessControl, ContentType, Currency, Economics, Manifest, Metadata, Provenance, Version,
-    Visibility,
+    normalize_tag, AccessControl, CanonicalVersion, ContentSection, ContentType, Currency,
+    Economics, Manifest, Metadata, Provenance, Version, Visibility,
 };
 
 use crate::error::{Result, StoreError};
+use crate::manifest_index::{ManifestIndex, ManifestIndexStats, DEFAULT_MANIFEST_INDEX_BYTES};
 use crate::traits::ManifestStore;
 use crate::types::ManifestFilter;
 
 /// SQLite-based manifest store.
 pub struct SqliteManifestStore {
     conn: Arc<Mutex<Connection>>,
+    /// Manifests held in memory, kept in step with updates and deletes.
+    hot: Mutex<HashMap<Hash, Manifest>>,
+    /// Recently loaded manifests, dropped on updates and deletes.
+    index: Mutex<ManifestIndex>,
 }
 
 impl SqliteManifestStore {
     /// Create a new manifest store with the given database connection.
     pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
-        Self { conn }
+        Self {
+            conn,
+            hot: Mutex::new(HashMap::new()),
+            index: Mutex::new(ManifestIndex::new(DEFAULT_MANIFEST_INDEX_BYTES)),
+        }
+    }
+
+    /// Set the memory budget of the manifest index, in bytes.
+    ///
+    /// Shrinking evicts the least recently used manifests; 0 disables the
+    /// index.
+    pub fn set_index_capacity(&self, max_bytes: usize) {
+        if let Ok(mut index) = self.index.lock() {
+            index.set_max_bytes(max_bytes);
+        }
+    }
+
+    /// Hit, miss and size counters of the manifest index.
+    pub fn index_stats(&self) -> ManifestIndexStats {
+        self.index
+            .lock()
+            .map(|index| index.stats())
+            .unwrap_or_default()
+    }
+
+    /// Keep the manifests of these hashes in memory, in place of the
+    /// previous set.
+    ///
+    /// Loads of hot manifests skip the database. Hashes without a stored
+    /// manifest are ignored. Returns the number of manifests held.
+    pub fn retain_hot(&self, hashes: &[Hash]) -> Result<usize> {
+        let mut hot = HashMap::with_capacity(hashes.len());
+        for hash in hashes {
+            if let Some(manifest) = self.load_stored(hash)? {
+                hot.insert(*hash, manifest);
+            }
+        }
+        let count = hot.len();
+        *self
+            .hot
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("hot manifest lock poisoned"))? = hot;
+        Ok(count)
+    }
+
+    /// Whether a manifest is held in memory.
+    pub fn is_hot(&self, hash: &Hash) -> bool {
+        self.hot
+            .lock()
+            .map(|hot| hot.contains_key(hash))
+            .unwrap_or(false)
+    }
+
+    /// Number of manifests held in memory.
+    pub fn hot_count(&self) -> usize {
+        self.hot.lock().map(|hot| hot.len()).unwrap_or(0)
+    }
+
+    /// Load a manifest from the database.
+    fn load_stored(&self, hash: &Hash) -> Result<Option<Manifest>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+        let hash_bytes = hash.0.to_vec();
+
+        let manifest = conn
+            .prepare_cached(
+                "SELECT hash, content_type, owner, version_number, version_previous,
+                        version_root, version_timestamp, visibility, title, description,
+                        tags, content_size, mime_type, price, total_queries,
+                        total_revenue, access_control, provenance, created_at, updated_at,
+                        metadata_schema, metadata_fields, preview_policy, bond, chunks, version_forked, pricing,
+                        sections, metering, referral
+                 FROM manifests WHERE hash = ?1",
+            )?
+            .query_row([hash_bytes], Self::deserialize_row)
+            .optional()?;
+
+        Ok(manifest)
+    }
+
+    /// Insert a manifest unless one with its hash exists, returning whether
+    /// it was inserted.
+    fn insert(conn: &Connection, manifest: &Manifest) -> Result<bool> {
+        let (
+            hash,
+            content_type,
+            owner,
+            version_number,
+            version_previous,
+            version_root,
+            version_timestamp,
+            visibility,
+            title,
+            description,
+            tags,
+            content_size,
+            mime_type,
+            price,
+            total_queries,
+            total_revenue,
+            access_control,
+            provenance,
+            created_at,
+            updated_at,
+            metadata_schema,
+            metadata_fields,
+            preview_policy,
+            bond,
+            chunks,
+            version_forked,
+            pricing,
+            sections,
+            metering,
+            referral,
+        ) = Self::serialize_manifest(manifest)?;
+
+        let inserted = conn
+            .prepare_cached(
+                "INSERT OR IGNORE INTO manifests (
+                hash, content_type, owner, version_number, version_previous,
+                version_root, version_timestamp, visibility, title, description,
+                tags, content_size, mime_type, price, total_queries,
+                total_revenue, access_control, provenance, created_at, updated_at,
+                metadata_schema, metadata_fields, preview_policy, bond, chunks, version_forked, pricing,
+                        sections, metering, referral
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10,
+                      ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25,
+                      ?26, ?27, ?28, ?29, ?30)",
+            )?
+            .execute(params![
+                hash,
+                content_type,
+                owner,
+                version_number,
+                version_previous,
+                version_root,
+                version_timestamp,
+                visibility,
+                title,
+                description,
+                tags,
+                content_size,
+                mime_type,
+                price,
+                total_queries,
+                total_revenue,
+                access_control,
+                provenance,
+                created_at,
+                updated_at,
+                metadata_schema,
+                metadata_fields,
+                preview_policy,
+                bond,
+                chunks,
+                version_forked,
+                pricing,
+                sections,
+                metering,
+                referral,
+            ])?;
+
+        Ok(inserted > 0)
     }
 
     /// Serialize a manifest to SQL row values.
@@ -52,6 +225,16 @@ impl SqliteManifestStore {
         String,          // provenance (JSON)
         Timestamp,       // created_at
         Timestamp,       // updated_at
+        Option<String>,  // metadata_schema
+        Option<String>,  // metadata_fields
+        Option<String>,  // preview_policy (JSON)
+        Option<String>,  // bond (JSON)
+        Option<String>,  // chunks (JSON)
+        bool,            // version_forked
+        Option<String>,  // pricing (JSON)
+        Option<String>,  // sections (JSON)
+        Option<String>,  // metering (JSON)
+        Option<String>,  // referral (JSON)
     )> {
         let hash = manifest.hash.0.to_vec();
         let content_type = manifest.content_type as u8;
@@ -77,6 +260,49 @@ impl SqliteManifestStore {
         let provenance = serde_json::to_string(&manifest.provenance)?;
         let created_at = manifest.created_at;
         let updated_at = manifest.updated_at;
+        let metadata_schema = manifest.metadata.schema.clone();
+        let metadata_fields = manifest.metadata.fields.clone();
+        let preview_policy = manifest
+            .metadata
+            .preview_policy
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        let bond = manifest
+            .economics
+            .bond
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        let chunks = manifest
+            .metadata
+            .chunks
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        let pricing = manifest
+            .economics
+            .pricing
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        let sections = if manifest.metadata.sections.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(&manifest.metadata.sections)?)
+        };
+        let metering = manifest
+            .metadata
+            .metering
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        let referral = manifest
+            .economics
+            .referral
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
 
         Ok((
             hash,
@@ -99,6 +325,16 @@ impl SqliteManifestStore {
             provenance,
             created_at,
             updated_at,
+            metadata_schema,
+            metadata_fields,
+            preview_policy,
+            bond,
+            chunks,
+            manifest.version.forked,
+            pricing,
+            sections,
+            metering,
+            referral,
         ))
     }
 
@@ -124,6 +360,16 @@ impl SqliteManifestStore {
         let provenance_json: String = row.get(17)?;
         let created_at: Timestamp = row.get(18)?;
         let updated_at: Timestamp = row.get(19)?;
+        let schema: Option<String> = row.get(20)?;
+        let fields: Option<String> = row.get(21)?;
+        let preview_policy_json: Option<String> = row.get(22)?;
+        let bond_json: Option<String> = row.get(23)?;
+        let chunks_json: Option<String> = row.get(24)?;
+        let version_forked: bool = row.get(25)?;
+        let pricing_json: Option<String> = row.get(26)?;
+        let sections_json: Option<String> = row.get(27)?;
+        let metering_json: Option<String> = row.get(28)?;
+        let referral_json: Option<String> = row.get(29)?;
 
         // Convert bytes to types
         let hash = bytes_to_hash(&hash_bytes);
@@ -136,6 +382,7 @@ impl SqliteManifestStore {
             1 => ContentType::L1,
             2 => ContentType::L2,
             3 => ContentType::L3,
+            4 => ContentType::Collection,
             _ => ContentType::L0, // Default fallback
         };
 
@@ -153,6 +400,15 @@ impl SqliteManifestStore {
 
         let access: AccessControl = serde_json::from_str(&access_control_json).unwrap_or_default();
         let provenance: Provenance = serde_json::from_str(&provenance_json).unwrap_or_default();
+        let preview_policy = preview_policy_json.and_then(|j| serde_json::from_str(&j).ok());
+        let bond = bond_json.and_then(|j| serde_json::from_str(&j).ok());
+        let chunks = chunks_json.and_then(|j| serde_json::from_str(&j).ok());
+        let pricing = pricing_json.and_then(|j| serde_json::from_str(&j).ok());
+        let sections: Vec<ContentSection> = sections_json
+            .map(|j| serde_json::from_str(&j).unwrap_or_default())
+            .unwrap_or_default();
+        let metering = metering_json.and_then(|j| serde_json::from_str(&j).ok());
+        let referral = referral_json.and_then(|j| serde_json::from_str(&j).ok());
 
         Ok(Manifest {
             hash,
@@ -163,6 +419,7 @@ impl SqliteManifestStore {
                 previous: version_previous,
                 root: version_root,
                 timestamp: version_timestamp,
+                forked: version_forked,
             },
             visibility,
             access,
@@ -172,12 +429,21 @@ impl SqliteManifestStore {
                 tags,
                 content_size,
                 mime_type,
+                schema,
+                fields,
+                preview_policy,
+                chunks,
+                sections,
+                metering,
             },
             economics: Economics {
                 price,
                 currency: Currency::HBAR,
                 total_queries,
                 total_revenue,
+                bond,
+                pricing,
+                referral,
             },
             provenance,
             created_at,
@@ -187,92 +453,46 @@ impl SqliteManifestStore {
 }
 
 impl ManifestStore for SqliteManifestStore {
-    fn store(&mut self, manifest: &Manifest) -> Result<()> {
-        let (
-            hash,
-            content_type,
-            owner,
-            version_number,
-            version_previous,
-            version_root,
-            version_timestamp,
-            visibility,
-            title,
-            description,
-            tags,
-            content_size,
-            mime_type,
-            price,
-            total_queries,
-            total_revenue,
-            access_control,
-            provenance,
-            created_at,
-            updated_at,
-        ) = Self::serialize_manifest(manifest)?;
-
+    fn store(&self, manifest: &Manifest) -> Result<()> {
         let conn = self
             .conn
             .lock()
             .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
-        conn.execute(
-            "INSERT OR IGNORE INTO manifests (
-                hash, content_type, owner, version_number, version_previous,
-                version_root, version_timestamp, visibility, title, description,
-                tags, content_size, mime_type, price, total_queries,
-                total_revenue, access_control, provenance, created_at, updated_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10,
-                      ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)",
-            params![
-                hash,
-                content_type,
-                owner,
-                version_number,
-                version_previous,
-                version_root,
-                version_timestamp,
-                visibility,
-                title,
-                description,
-                tags,
-                content_size,
-                mime_type,
-                price,
-                total_queries,
-                total_revenue,
-                access_control,
-                provenance,
-                created_at,
-                updated_at,
-            ],
-        )?;
-
+        Self::insert(&conn, manifest)?;
         Ok(())
     }
 
-    fn load(&self, hash: &Hash) -> Result<Option<Manifest>> {
-        let conn = self
+    fn store_batch(&self, manifests: &[Manifest]) -> Result<usize> {
+        let mut conn = self
             .conn
             .lock()
             .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
-        let hash_bytes = hash.0.to_vec();
-
-        let manifest = conn
-            .query_row(
-                "SELECT hash, content_type, owner, version_number, version_previous,
-                        version_root, version_timestamp, visibility, title, description,
-                        tags, content_size, mime_type, price, total_queries,
-                        total_revenue, access_control, provenance, created_at, updated_at
-                 FROM manifests WHERE hash = ?1",
-                [hash_bytes],
-                Self::deserialize_row,
-            )
-            .optional()?;
+        let tx = conn.transaction()?;
+        let mut inserted = 0;
+        for manifest in manifests {
+            if Self::insert(&tx, manifest)? {
+                inserted += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(inserted)
+    }
 
+    fn load(&self, hash: &Hash) -> Result<Option<Manifest>> {
+        if let Some(manifest) = self.hot.lock().ok().and_then(|hot| hot.get(hash).cloned()) {
+            return Ok(Some(manifest));
+        }
+        if let Some(manifest) = self.index.lock().ok().and_then(|mut index| index.get(hash)) {
+            return Ok(Some(manifest));
+        }
+        let manifest = self.load_stored(hash)?;
+        if let (Some(manifest), Ok(mut index)) = (&manifest, self.index.lock()) {
+            index.insert(manifest);
+        }
         Ok(manifest)
     }
 
-    fn update(&mut self, manifest: &Manifest) -> Result<()> {
+    fn update(&self, manifest: &Manifest) -> Result<()> {
         let (
             hash,
             content_type,
@@ -294,21 +514,36 @@ impl ManifestStore for SqliteManifestStore {
             provenance,
             _created_at, // Don't update created_at
             updated_at,
+            metadata_schema,
+            metadata_fields,
+            preview_policy,
+            bond,
+            chunks,
+            version_forked,
+            pricing,
+            sections,
+            metering,
+            referral,
         ) = Self::serialize_manifest(manifest)?;
 
         let conn = self
             .conn
             .lock()
             .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
-        let rows_affected = conn.execute(
-            "UPDATE manifests SET
+        let rows_affected = conn
+            .prepare_cached(
+                "UPDATE manifests SET
                 content_type = ?2, owner = ?3, version_number = ?4, version_previous = ?5,
                 version_root = ?6, version_timestamp = ?7, visibility = ?8, title = ?9,
                 description = ?10, tags = ?11, content_size = ?12, mime_type = ?13,
                 price = ?14, total_queries = ?15, total_revenue = ?16,
-                access_control = ?17, provenance = ?18, updated_at = ?19
+                access_control = ?17, provenance = ?18, updated_at = ?19,
+                metadata_schema = ?20, metadata_fields = ?21, preview_policy = ?22,
+                bond = ?23, chunks = ?24, version_forked = ?25, pricing = ?26,
+                sections = ?27, metering = ?28, referral = ?29
              WHERE hash = ?1",
-            params![
+            )?
+            .execute(params![
                 hash,
                 content_type,
                 owner,
@@ -328,23 +563,52 @@ impl ManifestStore for SqliteManifestStore {
                 access_control,
                 provenance,
                 updated_at,
-            ],
-        )?;
+                metadata_schema,
+                metadata_fields,
+                preview_policy,
+                bond,
+                chunks,
+                version_forked,
+                pricing,
+                sections,
+                metering,
+                referral,
+            ])?;
 
         if rows_affected == 0 {
             return Err(StoreError::ManifestNotFound(manifest.hash));
         }
+        drop(conn);
+        if let Ok(mut index) = self.index.lock() {
+            index.remove(&manifest.hash);
+        }
+
+        // Refresh a hot copy with the manifest as stored
+        if self.is_hot(&manifest.hash) {
+            if let Some(stored) = self.load_stored(&manifest.hash)? {
+                if let Ok(mut hot) = self.hot.lock() {
+                    hot.insert(manifest.hash, stored);
+                }
+            }
+        }
 
         Ok(())
     }
 
-    fn delete(&mut self, hash: &Hash) -> Result<()> {
+    fn delete(&self, hash: &Hash) -> Result<()> {
         let conn = self
             .conn
             .lock()
             .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
         let hash_bytes = hash.0.to_vec();
-        conn.execute("DELETE FROM manifests WHERE hash = ?1", [hash_bytes])?;
+        conn.prepare_cached("DELETE FROM manifests WHERE hash = ?1")?
+            .execute([hash_bytes])?;
+        if let Ok(mut hot) = self.hot.lock() {
+            hot.remove(hash);
+        }
+        if let Ok(mut index) = self.index.lock() {
+            index.remove(hash);
+        }
         Ok(())
     }
 
@@ -358,7 +622,9 @@ impl ManifestStore for SqliteManifestStore {
             "SELECT hash, content_type, owner, version_number, version_previous,
                     version_root, version_timestamp, visibility, title, description,
                     tags, content_size, mime_type, price, total_queries,
-                    total_revenue, access_control, provenance, created_at, updated_at
+                    total_revenue, access_control, provenance, created_at, updated_at,
+                    metadata_schema, metadata_fields, preview_policy, bond, chunks, version_forked, pricing,
+                        sections, metering, referral
              FROM manifests WHERE 1=1",
         );
 
@@ -395,6 +661,47 @@ impl ManifestStore for SqliteManifestStore {
             param_idx += 1;
         }
 
+        if let Some(ref content_types) = filter.content_types {
+            let placeholders: Vec<String> = (0..content_types.len())
+                .map(|i| format!("?{}", param_idx + i))
+                .collect();
+            // An empty list matches nothing
+            sql.push_str(&format!(
+                " AND content_type IN ({})",
+                placeholders.join(", ")
+            ));
+            for content_type in content_types {
+                params_values.push(Box::new(*content_type as u8));
+            }
+            param_idx += content_types.len();
+        }
+
+        if let Some(min_price) = filter.min_price {
+            sql.push_str(&format!(" AND price >= ?{}", param_idx));
+            params_values.push(Box::new(min_price as i64));
+            param_idx += 1;
+        }
+
+        if let Some(max_price) = filter.max_price {
+            sql.push_str(&format!(" AND price <= ?{}", param_idx));
+            params_values.push(Box::new(max_price as i64));
+            param_idx += 1;
+        }
+
+        if let Some(ref tags) = filter.tags {
+            for tag in tags {
+                // A tag also matches its descendants (`science` matches `science/biology`)
+                sql.push_str(&format!(
+                    " AND EXISTS (SELECT 1 FROM json_each(COALESCE(manifests.tags, '[]')) WHERE LOWER(json_each.value) = ?{0} OR substr(LOWER(json_each.value), 1, length(?{0}) + 1) = ?{0} || '/')",
+                    param_idx
+                ));
+                params_values.push(Box::new(
+                    normalize_tag(tag).unwrap_or_else(|| tag.to_lowercase()),
+                ));
+                param_idx += 1;
+            }
+        }
+
         if let Some(ref text_query) = filter.text_query {
             let pattern = format!("%{}%", text_query.to_lowercase());
             sql.push_str(&format!(
@@ -442,7 +749,9 @@ impl ManifestStore for SqliteManifestStore {
             "SELECT hash, content_type, owner, version_number, version_previous,
                     version_root, version_timestamp, visibility, title, description,
                     tags, content_size, mime_type, price, total_queries,
-                    total_revenue, access_control, provenance, created_at, updated_at
+                    total_revenue, access_control, provenance, created_at, updated_at,
+                    metadata_schema, metadata_fields, preview_policy, bond, chunks, version_forked, pricing,
+                        sections, metering, referral
              FROM manifests WHERE version_root = ?1 ORDER BY version_number ASC",
         )?;
 
@@ -453,6 +762,39 @@ impl ManifestStore for SqliteManifestStore {
 
         Ok(manifests)
     }
+
+    fn set_canonical(&self, pointer: &CanonicalVersion) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let data = serde_json::to_string(pointer)?;
+        let stored = conn.execute(
+            "INSERT INTO canonical_versions (version_root, data, created_at)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(version_root) DO UPDATE SET
+                data = excluded.data, created_at = excluded.created_at
+             WHERE excluded.created_at > canonical_versions.created_at",
+            params![pointer.root.0.to_vec(), data, pointer.created_at as i64],
+        )?;
+
+        Ok(stored > 0)
+    }
+
+    fn get_canonical(&self, version_root: &Hash) -> Result<Option<CanonicalVersion>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let data: Option<String> = conn
+            .prepare_cached("SELECT data FROM canonical_versions WHERE version_root = ?1")?
+            .query_row([version_root.0.to_vec()], |row| row.get(0))
+            .optional()?;
+
+        Ok(data.map(|data| serde_json::from_str(&data)).transpose()?)
+    }
 }
 
 /// Convert bytes to Hash.
@@ -478,6 +820,8 @@ mod tests {
     use super::*;
     use crate::schema::initialize_schema;
     use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_types::{ChunkTree, PreviewPolicy, PricingRule, PublisherBond, ReferralPolicy};
+    use nodalync_wire::SearchFilters;
     use rusqlite::Connection;
 
     fn setup_store() -> SqliteManifestStore {
@@ -496,7 +840,7 @@ mod tests {
 
     #[test]
     fn test_store_and_load() {
-        let mut store = setup_store();
+        let store = setup_store();
         let manifest = test_manifest();
 
         store.store(&manifest).unwrap();
@@ -511,9 +855,68 @@ mod tests {
         assert_eq!(loaded.metadata.title, manifest.metadata.title);
     }
 
+    #[test]
+    fn test_hot_manifests() {
+        let store = setup_store();
+        let manifest = test_manifest();
+        store.store(&manifest).unwrap();
+
+        let missing = content_hash(b"missing");
+        assert_eq!(store.retain_hot(&[manifest.hash, missing]).unwrap(), 1);
+        assert!(store.is_hot(&manifest.hash));
+        assert_eq!(store.load(&manifest.hash).unwrap(), Some(manifest.clone()));
+
+        // Updates reach the hot copy, deletes drop it
+        let mut updated = manifest.clone();
+        updated.metadata.title = "Updated".to_string();
+        store.update(&updated).unwrap();
+        assert_eq!(
+            store.load(&manifest.hash).unwrap().unwrap().metadata.title,
+            "Updated"
+        );
+        store.delete(&manifest.hash).unwrap();
+        assert!(!store.is_hot(&manifest.hash));
+        assert!(store.load(&manifest.hash).unwrap().is_none());
+
+        // A new set replaces the old one
+        store.store(&manifest).unwrap();
+        store.retain_hot(&[manifest.hash]).unwrap();
+        assert_eq!(store.retain_hot(&[]).unwrap(), 0);
+        assert_eq!(store.hot_count(), 0);
+    }
+
+    #[test]
+    fn test_manifest_index() {
+        let store = setup_store();
+        let manifest = test_manifest();
+        store.store(&manifest).unwrap();
+
+        store.load(&manifest.hash).unwrap();
+        assert_eq!(store.load(&manifest.hash).unwrap(), Some(manifest.clone()));
+        let stats = store.index_stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+
+        // Writes invalidate the indexed copy
+        let mut updated = manifest.clone();
+        updated.metadata.title = "Updated".to_string();
+        store.update(&updated).unwrap();
+        assert_eq!(
+            store.load(&manifest.hash).unwrap().unwrap().metadata.title,
+            "Updated"
+        );
+        store.delete(&manifest.hash).unwrap();
+        assert!(store.load(&manifest.hash).unwrap().is_none());
+        assert_eq!(store.index_stats().entries, 0);
+
+        store.set_index_capacity(0);
+        store.store(&manifest).unwrap();
+        store.load(&manifest.hash).unwrap();
+        assert_eq!(store.index_stats().entries, 0);
+    }
+
     #[test]
     fn test_store_idempotent() {
-        let mut store = setup_store();
+        let store = setup_store();
         let manifest = test_manifest();
 
         store.store(&manifest).unwrap();
@@ -523,6 +926,25 @@ mod tests {
         assert!(loaded.is_some());
     }
 
+    #[test]
+    fn test_store_batch() {
+        let store = setup_store();
+        let existing = test_manifest();
+        store.store(&existing).unwrap();
+
+        let mut fresh = test_manifest();
+        fresh.hash = content_hash(b"batch content");
+        let inserted = store
+            .store_batch(&[existing.clone(), fresh.clone()])
+            .unwrap();
+
+        // Only the new manifest counts; the existing row is left alone
+        assert_eq!(inserted, 1);
+        assert_eq!(store.load(&fresh.hash).unwrap(), Some(fresh));
+        assert_eq!(store.load(&existing.hash).unwrap(), Some(existing));
+        assert_eq!(store.store_batch(&[]).unwrap(), 0);
+    }
+
     #[test]
     fn test_load_nonexistent() {
         let store = setup_store();
@@ -534,7 +956,7 @@ mod tests {
 
     #[test]
     fn test_update() {
-        let mut store = setup_store();
+        let store = setup_store();
         let mut manifest = test_manifest();
 
         store.store(&manifest).unwrap();
@@ -552,7 +974,7 @@ mod tests {
 
     #[test]
     fn test_update_nonexistent() {
-        let mut store = setup_store();
+        let store = setup_store();
         let manifest = test_manifest();
 
         let result = store.update(&manifest);
@@ -561,7 +983,7 @@ mod tests {
 
     #[test]
     fn test_delete() {
-        let mut store = setup_store();
+        let store = setup_store();
         let manifest = test_manifest();
 
         store.store(&manifest).unwrap();
@@ -573,7 +995,7 @@ mod tests {
 
     #[test]
     fn test_list_no_filter() {
-        let mut store = setup_store();
+        let store = setup_store();
 
         let m1 = test_manifest();
         let mut m2 = test_manifest();
@@ -593,7 +1015,7 @@ mod tests {
 
     #[test]
     fn test_list_with_visibility_filter() {
-        let mut store = setup_store();
+        let store = setup_store();
 
         let mut m1 = test_manifest();
         m1.visibility = Visibility::Private;
@@ -611,9 +1033,104 @@ mod tests {
         assert_eq!(manifests[0].visibility, Visibility::Shared);
     }
 
+    #[test]
+    fn test_list_with_search_filters() {
+        let store = setup_store();
+
+        let mut cheap = test_manifest();
+        cheap.economics.price = 10;
+        cheap.metadata.tags = vec!["Physics".to_string(), "quantum".to_string()];
+        cheap.created_at = 1000;
+
+        let mut pricey = test_manifest();
+        pricey.hash = content_hash(b"pricey content");
+        pricey.content_type = ContentType::L3;
+        pricey.economics.price = 500;
+        pricey.metadata.tags = vec!["physics".to_string()];
+        pricey.created_at = 2000;
+
+        store.store(&cheap).unwrap();
+        store.store(&pricey).unwrap();
+
+        let hashes = |filter: ManifestFilter| -> Vec<Hash> {
+            store.list(filter).unwrap().iter().map(|m| m.hash).collect()
+        };
+
+        assert_eq!(
+            hashes(ManifestFilter::new().min_price(100)),
+            vec![pricey.hash]
+        );
+        assert_eq!(
+            hashes(ManifestFilter::new().max_price(100)),
+            vec![cheap.hash]
+        );
+        assert_eq!(
+            hashes(
+                ManifestFilter::new()
+                    .with_tag("PHYSICS")
+                    .with_tag("Quantum")
+            ),
+            vec![cheap.hash]
+        );
+        assert_eq!(hashes(ManifestFilter::new().with_tag("phys")), vec![]);
+
+        assert_eq!(
+            hashes(
+                ManifestFilter::new().with_content_types(vec![ContentType::L1, ContentType::L3])
+            ),
+            vec![pricey.hash]
+        );
+        assert_eq!(
+            hashes(ManifestFilter::new().with_content_types(vec![])),
+            vec![]
+        );
+
+        let filters = SearchFilters {
+            content_types: Some(vec![ContentType::L0, ContentType::L3]),
+            created_after: Some(1500),
+            tags: Some(vec!["physics".to_string()]),
+            owner: Some(pricey.owner),
+            ..Default::default()
+        };
+        assert_eq!(
+            hashes(ManifestFilter::new().with_search_filters(&filters)),
+            vec![pricey.hash]
+        );
+    }
+
+    #[test]
+    fn test_tag_filter_matches_descendants() {
+        let store = setup_store();
+
+        let mut biology = test_manifest();
+        biology.metadata.tags = vec!["science/biology".to_string()];
+        store.store(&biology).unwrap();
+
+        let mut sciences = test_manifest();
+        sciences.hash = content_hash(b"sciences content");
+        sciences.metadata.tags = vec!["sciences".to_string()];
+        store.store(&sciences).unwrap();
+
+        let hashes = |filter: ManifestFilter| -> Vec<Hash> {
+            store.list(filter).unwrap().iter().map(|m| m.hash).collect()
+        };
+        assert_eq!(
+            hashes(ManifestFilter::new().with_tag(" Science ")),
+            vec![biology.hash]
+        );
+        assert_eq!(
+            hashes(ManifestFilter::new().with_tag("science/biology")),
+            vec![biology.hash]
+        );
+        assert_eq!(
+            hashes(ManifestFilter::new().with_tag("science/bio")),
+            vec![]
+        );
+    }
+
     #[test]
     fn test_list_with_limit() {
-        let mut store = setup_store();
+        let store = setup_store();
 
         for i in 0..5 {
             let mut m = test_manifest();
@@ -628,7 +1145,7 @@ mod tests {
 
     #[test]
     fn test_get_versions() {
-        let mut store = setup_store();
+        let store = setup_store();
 
         // Create v1
         let mut m1 = test_manifest();
@@ -645,6 +1162,7 @@ mod tests {
             previous: Some(m1.hash),
             root: version_root,
             timestamp: 2000,
+            forked: false,
         };
 
         store.store(&m2).unwrap();
@@ -657,7 +1175,7 @@ mod tests {
 
     #[test]
     fn test_store_and_load_offline_visibility() {
-        let mut store = setup_store();
+        let store = setup_store();
         let mut manifest = test_manifest();
         manifest.visibility = Visibility::Offline;
 
@@ -669,7 +1187,7 @@ mod tests {
 
     #[test]
     fn test_manifest_with_metadata() {
-        let mut store = setup_store();
+        let store = setup_store();
 
         let (_, public_key) = generate_identity();
         let owner = peer_id_from_public_key(&public_key);
@@ -690,5 +1208,168 @@ mod tests {
         );
         assert_eq!(loaded.metadata.tags, vec!["tag1", "tag2"]);
         assert_eq!(loaded.metadata.mime_type, Some("text/plain".to_string()));
+        assert!(loaded.metadata.preview_policy.is_none());
+    }
+
+    #[test]
+    fn test_manifest_preview_policy_roundtrip() {
+        let store = setup_store();
+        let mut manifest = test_manifest();
+        store.store(&manifest).unwrap();
+
+        let policy = PreviewPolicy::new()
+            .with_pattern(r"\d{4}")
+            .with_section("Appendix");
+        manifest.metadata.preview_policy = Some(policy.clone());
+        store.update(&manifest).unwrap();
+
+        let loaded = store.load(&manifest.hash).unwrap().unwrap();
+        assert_eq!(loaded.metadata.preview_policy, Some(policy));
+    }
+
+    #[test]
+    fn test_manifest_bond_roundtrip() {
+        let store = setup_store();
+        let mut manifest = test_manifest();
+        manifest.economics.bond = Some(PublisherBond::new(5_000, "0.0.1@1.2"));
+        store.store(&manifest).unwrap();
+
+        let loaded = store.load(&manifest.hash).unwrap().unwrap();
+        assert_eq!(loaded.economics.bond, manifest.economics.bond);
+
+        manifest.economics.bond = None;
+        store.update(&manifest).unwrap();
+        let loaded = store.load(&manifest.hash).unwrap().unwrap();
+        assert!(loaded.economics.bond.is_none());
+    }
+
+    #[test]
+    fn test_manifest_pricing_roundtrip() {
+        let store = setup_store();
+        let mut manifest = test_manifest();
+        manifest.economics.pricing = Some(PricingRule::Surge {
+            max_price: 5_000,
+            window_ms: 3_600_000,
+            step_bps: 500,
+        });
+        store.store(&manifest).unwrap();
+
+        let loaded = store.load_stored(&manifest.hash).unwrap().unwrap();
+        assert_eq!(loaded.economics.pricing, manifest.economics.pricing);
+
+        manifest.economics.pricing = None;
+        store.update(&manifest).unwrap();
+        let loaded = store.load_stored(&manifest.hash).unwrap().unwrap();
+        assert!(loaded.economics.pricing.is_none());
+    }
+
+    #[test]
+    fn test_manifest_chunks_roundtrip() {
+        let store = setup_store();
+        let mut manifest = test_manifest();
+        manifest.metadata.chunks = Some(ChunkTree::build(b"ten bytes!", 4));
+        store.store(&manifest).unwrap();
+
+        let loaded = store.load(&manifest.hash).unwrap().unwrap();
+        assert_eq!(loaded.metadata.chunks, manifest.metadata.chunks);
+
+        manifest.metadata.chunks = None;
+        store.update(&manifest).unwrap();
+        let loaded = store.load(&manifest.hash).unwrap().unwrap();
+        assert!(loaded.metadata.chunks.is_none());
+    }
+
+    #[test]
+    fn test_manifest_sections_roundtrip() {
+        let store = setup_store();
+        let mut manifest = test_manifest();
+        let content = b"intro, then the details";
+        manifest.metadata.sections = vec![
+            ContentSection::new("Intro", content, 0..5, 10),
+            ContentSection::new("Details", content, 7..content.len(), 40),
+        ];
+        store.store(&manifest).unwrap();
+
+        let loaded = store.load_stored(&manifest.hash).unwrap().unwrap();
+        assert_eq!(loaded.metadata.sections, manifest.metadata.sections);
+
+        manifest.metadata.sections.clear();
+        store.update(&manifest).unwrap();
+        let loaded = store.load_stored(&manifest.hash).unwrap().unwrap();
+        assert!(loaded.metadata.sections.is_empty());
+    }
+
+    #[test]
+    fn test_manifest_metering_roundtrip() {
+        let store = setup_store();
+        let mut manifest = test_manifest();
+        manifest.metadata.metering = Some(ChunkTree::build(&[1u8; 3_000], 1_024));
+        store.store(&manifest).unwrap();
+
+        let loaded = store.load_stored(&manifest.hash).unwrap().unwrap();
+        assert_eq!(loaded.metadata.metering, manifest.metadata.metering);
+
+        manifest.metadata.metering = None;
+        store.update(&manifest).unwrap();
+        let loaded = store.load_stored(&manifest.hash).unwrap().unwrap();
+        assert!(loaded.metadata.metering.is_none());
+    }
+
+    #[test]
+    fn test_manifest_referral_roundtrip() {
+        let store = setup_store();
+        let mut manifest = test_manifest();
+        manifest.economics.referral = Some(ReferralPolicy::new(750));
+        store.store(&manifest).unwrap();
+
+        let loaded = store.load_stored(&manifest.hash).unwrap().unwrap();
+        assert_eq!(loaded.economics.referral, manifest.economics.referral);
+
+        manifest.economics.referral = None;
+        store.update(&manifest).unwrap();
+        let loaded = store.load_stored(&manifest.hash).unwrap().unwrap();
+        assert!(loaded.economics.referral.is_none());
+    }
+
+    #[test]
+    fn test_version_forked_roundtrip() {
+        let store = setup_store();
+        let mut manifest = test_manifest();
+        store.store(&manifest).unwrap();
+        assert!(!store.load(&manifest.hash).unwrap().unwrap().version.forked);
+
+        manifest.version.forked = true;
+        store.update(&manifest).unwrap();
+        assert!(store.load(&manifest.hash).unwrap().unwrap().version.forked);
+        assert!(
+            store.get_versions(&manifest.hash).unwrap()[0]
+                .version
+                .forked
+        );
+    }
+
+    #[test]
+    fn test_canonical_pointer_keeps_newest() {
+        let store = setup_store();
+        let (_, public_key) = generate_identity();
+        let owner = peer_id_from_public_key(&public_key);
+        let root = content_hash(b"v1");
+        let pointer = |version: &[u8], created_at| {
+            CanonicalVersion::new(root, content_hash(version), owner, public_key, created_at)
+        };
+
+        assert!(store.get_canonical(&root).unwrap().is_none());
+        assert!(store.set_canonical(&pointer(b"v3a", 2000)).unwrap());
+        assert!(!store.set_canonical(&pointer(b"v3b", 1000)).unwrap());
+        assert_eq!(
+            store.get_canonical(&root).unwrap().unwrap().version,
+            content_hash(b"v3a")
+        );
+
+        assert!(store.set_canonical(&pointer(b"v3b", 3000)).unwrap());
+        assert_eq!(
+            store.get_canonical(&root).unwrap().unwrap().version,
+            content_hash(b"v3b")
+        );
     }
 }
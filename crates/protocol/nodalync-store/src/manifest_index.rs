@@ -0,0 +1,235 @@
+//! In-memory manifest index.
+//!
+//! Previews, queries and searches look manifests up by hash far more often
+//! than manifests change. [`SqliteManifestStore`](crate::SqliteManifestStore)
+//! keeps recently loaded manifests in memory, up to a byte budget, so
+//! repeat lookups skip the database. Entries are filled on reads and dropped
+//! whenever the manifest is written; the least recently used entries are
+//! evicted first.
+
+use std::collections::{BTreeMap, HashMap};
+use std::mem::size_of;
+
+use nodalync_crypto::Hash;
+use nodalync_types::{ContentSection, Manifest, ProvenanceEntry};
+
+/// Default memory budget for the manifest index (16 MiB).
+pub const DEFAULT_MANIFEST_INDEX_BYTES: usize = 16 * 1024 * 1024;
+
+/// Snapshot of manifest index counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ManifestIndexStats {
+    /// Lookups answered from memory.
+    pub hits: u64,
+    /// Lookups that went to the database.
+    pub misses: u64,
+    /// Entries dropped to stay within the budget.
+    pub evictions: u64,
+    /// Manifests currently held.
+    pub entries: usize,
+    /// Estimated bytes currently held.
+    pub bytes: usize,
+    /// Memory budget in bytes (0 disables the index).
+    pub max_bytes: usize,
+}
+
+impl ManifestIndexStats {
+    /// Fraction of lookups answered from memory, or 0 with no lookups.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+struct Entry {
+    manifest: Manifest,
+    size: usize,
+    last_used: u64,
+}
+
+/// Least recently used manifests, bounded by estimated size.
+pub(crate) struct ManifestIndex {
+    max_bytes: usize,
+    entries: HashMap<Hash, Entry>,
+    /// Entries by last use, oldest first.
+    recency: BTreeMap<u64, Hash>,
+    clock: u64,
+    bytes: usize,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl ManifestIndex {
+    pub(crate) fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+            bytes: 0,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    /// Look up a manifest, counting a hit or miss.
+    pub(crate) fn get(&mut self, hash: &Hash) -> Option<Manifest> {
+        self.clock += 1;
+        let Some(entry) = self.entries.get_mut(hash) else {
+            self.misses += 1;
+            return None;
+        };
+        self.recency.remove(&entry.last_used);
+        entry.last_used = self.clock;
+        self.recency.insert(self.clock, *hash);
+        self.hits += 1;
+        Some(entry.manifest.clone())
+    }
+
+    /// Hold a manifest, evicting older entries to make room.
+    ///
+    /// Manifests larger than the whole budget are not held.
+    pub(crate) fn insert(&mut self, manifest: &Manifest) {
+        self.remove(&manifest.hash);
+        let size = estimated_size(manifest);
+        if size > self.max_bytes {
+            return;
+        }
+        self.evict_to(self.max_bytes - size);
+
+        self.clock += 1;
+        self.recency.insert(self.clock, manifest.hash);
+        self.entries.insert(
+            manifest.hash,
+            Entry {
+                manifest: manifest.clone(),
+                size,
+                last_used: self.clock,
+            },
+        );
+        self.bytes += size;
+    }
+
+    /// Drop a manifest, if held.
+    pub(crate) fn remove(&mut self, hash: &Hash) {
+        if let Some(entry) = self.entries.remove(hash) {
+            self.recency.remove(&entry.last_used);
+            self.bytes -= entry.size;
+        }
+    }
+
+    /// Change the budget, evicting down to it.
+    pub(crate) fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.max_bytes = max_bytes;
+        self.evict_to(max_bytes);
+    }
+
+    /// Evict least recently used entries until at most `bytes` are held.
+    fn evict_to(&mut self, bytes: usize) {
+        while self.bytes > bytes {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            if let Some(entry) = self.entries.remove(&oldest) {
+                self.bytes -= entry.size;
+                self.evictions += 1;
+            }
+        }
+    }
+
+    pub(crate) fn stats(&self) -> ManifestIndexStats {
+        ManifestIndexStats {
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+            entries: self.entries.len(),
+            bytes: self.bytes,
+            max_bytes: self.max_bytes,
+        }
+    }
+}
+
+/// Rough heap and inline size of a manifest.
+fn estimated_size(manifest: &Manifest) -> usize {
+    let metadata = &manifest.metadata;
+    let provenance = &manifest.provenance;
+    size_of::<Manifest>()
+        + metadata.title.len()
+        + metadata.description.as_ref().map_or(0, String::len)
+        + metadata.tags.iter().map(String::len).sum::<usize>()
+        + metadata.mime_type.as_ref().map_or(0, String::len)
+        + metadata.schema.as_ref().map_or(0, String::len)
+        + metadata.fields.as_ref().map_or(0, String::len)
+        + metadata
+            .chunks
+            .as_ref()
+            .map_or(0, |chunks| chunks.hashes.len() * size_of::<Hash>())
+        + metadata
+            .sections
+            .iter()
+            .map(|section| size_of::<ContentSection>() + section.title.len())
+            .sum::<usize>()
+        + metadata
+            .metering
+            .as_ref()
+            .map_or(0, |units| units.hashes.len() * size_of::<Hash>())
+        + provenance.root_l0l1.len() * size_of::<ProvenanceEntry>()
+        + provenance.derived_from.len() * size_of::<Hash>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, PeerId};
+    use nodalync_types::Metadata;
+
+    fn manifest(i: u8) -> Manifest {
+        let hash = content_hash(&[i]);
+        Manifest::new_l0(hash, PeerId([i; 20]), Metadata::new("Doc", 10), 1)
+    }
+
+    #[test]
+    fn test_hits_misses_and_lru_eviction() {
+        let size = estimated_size(&manifest(0));
+        let mut index = ManifestIndex::new(size * 2);
+
+        index.insert(&manifest(0));
+        index.insert(&manifest(1));
+        assert!(index.get(&manifest(0).hash).is_some());
+
+        // Manifest 1 was used least recently, so it makes room for 2
+        index.insert(&manifest(2));
+        assert!(index.get(&manifest(1).hash).is_none());
+        assert!(index.get(&manifest(0).hash).is_some());
+        assert!(index.get(&manifest(2).hash).is_some());
+
+        let stats = index.stats();
+        assert_eq!((stats.hits, stats.misses, stats.evictions), (3, 1, 1));
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.bytes, size * 2);
+        assert_eq!(stats.hit_rate(), 0.75);
+    }
+
+    #[test]
+    fn test_remove_and_shrink() {
+        let size = estimated_size(&manifest(0));
+        let mut index = ManifestIndex::new(size * 2);
+        index.insert(&manifest(0));
+        index.insert(&manifest(1));
+
+        index.remove(&manifest(0).hash);
+        assert_eq!(index.stats().bytes, size);
+        index.set_max_bytes(0);
+        assert_eq!(index.stats().entries, 0);
+
+        // A zero budget holds nothing
+        index.insert(&manifest(0));
+        assert!(index.get(&manifest(0).hash).is_none());
+    }
+}
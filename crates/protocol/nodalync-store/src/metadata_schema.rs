@@ -0,0 +1,123 @@
+//! Metadata schema cache.
+//!
+//! This module caches the JSON schemas that structured metadata fields are
+//! validated against, keyed by schema URI.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use crate::error::{Result, StoreError};
+use crate::traits::MetadataSchemaStore;
+
+/// SQLite-based metadata schema cache.
+pub struct SqliteMetadataSchemaStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteMetadataSchemaStore {
+    /// Create a new schema cache with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+}
+
+impl MetadataSchemaStore for SqliteMetadataSchemaStore {
+    fn put(&self, uri: &str, schema: &str) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO metadata_schemas (uri, schema) VALUES (?1, ?2)",
+            params![uri, schema],
+        )?;
+
+        Ok(())
+    }
+
+    fn get(&self, uri: &str) -> Result<Option<String>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let schema = conn
+            .query_row(
+                "SELECT schema FROM metadata_schemas WHERE uri = ?1",
+                [uri],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(schema)
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare("SELECT uri FROM metadata_schemas ORDER BY uri ASC")?;
+        let uris = stmt
+            .query_map([], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(uris)
+    }
+
+    fn remove(&self, uri: &str) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        conn.execute("DELETE FROM metadata_schemas WHERE uri = ?1", [uri])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+
+    fn setup_store() -> SqliteMetadataSchemaStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteMetadataSchemaStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    #[test]
+    fn test_put_get_list_remove() {
+        let store = setup_store();
+        assert!(store.get("example:paper").unwrap().is_none());
+
+        store.put("example:paper", r#"{"type":"object"}"#).unwrap();
+        store
+            .put("example:dataset", r#"{"type":"object"}"#)
+            .unwrap();
+        assert_eq!(
+            store.get("example:paper").unwrap().as_deref(),
+            Some(r#"{"type":"object"}"#)
+        );
+        assert_eq!(
+            store.list().unwrap(),
+            vec!["example:dataset".to_string(), "example:paper".to_string()]
+        );
+
+        // Re-caching replaces the schema
+        store
+            .put("example:paper", r#"{"type":"object","required":["doi"]}"#)
+            .unwrap();
+        assert!(store.get("example:paper").unwrap().unwrap().contains("doi"));
+        assert_eq!(store.list().unwrap().len(), 2);
+
+        store.remove("example:paper").unwrap();
+        store.remove("example:missing").unwrap();
+        assert!(store.get("example:paper").unwrap().is_none());
+        assert_eq!(store.list().unwrap(), vec!["example:dataset".to_string()]);
+    }
+}
@@ -0,0 +1,260 @@
+//! Content report and moderation storage.
+//!
+//! Reports are kept per reporter, so a peer reporting the same content
+//! twice counts once. Reported content enters a review queue as
+//! `pending`; its status changes when the moderation policy hides it
+//! automatically or the node operator hides or allows it.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{Hash, Timestamp};
+use nodalync_types::ContentReport;
+
+use crate::error::{Result, StoreError};
+use crate::traits::ModerationStore;
+use crate::types::{ModerationEntry, ModerationStatus};
+
+/// SQLite-based moderation store.
+pub struct SqliteModerationStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteModerationStore {
+    /// Create a new moderation store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+}
+
+fn parse_status(status: &str) -> Result<ModerationStatus> {
+    ModerationStatus::parse(status)
+        .ok_or_else(|| StoreError::InvalidData(format!("unknown moderation status: {}", status)))
+}
+
+/// Convert bytes to Hash.
+fn bytes_to_hash(bytes: &[u8]) -> Hash {
+    let mut arr = [0u8; 32];
+    if bytes.len() >= 32 {
+        arr.copy_from_slice(&bytes[..32]);
+    }
+    Hash(arr)
+}
+
+impl ModerationStore for SqliteModerationStore {
+    fn store_report(&self, report: &ContentReport, received_at: Timestamp) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let data = serde_json::to_string(report)?;
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO content_reports
+                (content_hash, reporter, reason, data, received_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                report.content_hash.0.to_vec(),
+                report.reporter.0.to_vec(),
+                report.reason.as_str(),
+                data,
+                received_at as i64,
+            ],
+        )?;
+
+        if inserted > 0 {
+            conn.execute(
+                "INSERT OR IGNORE INTO moderation (content_hash, status, updated_at)
+                 VALUES (?1, ?2, ?3)",
+                params![
+                    report.content_hash.0.to_vec(),
+                    ModerationStatus::Pending.as_str(),
+                    received_at as i64,
+                ],
+            )?;
+        }
+
+        Ok(inserted > 0)
+    }
+
+    fn reports_for(&self, content_hash: &Hash) -> Result<Vec<ContentReport>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT data FROM content_reports WHERE content_hash = ?1
+             ORDER BY received_at ASC, reporter ASC",
+        )?;
+        let rows = stmt
+            .query_map([content_hash.0.to_vec()], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        rows.iter()
+            .map(|data| Ok(serde_json::from_str(data)?))
+            .collect()
+    }
+
+    fn get_status(&self, content_hash: &Hash) -> Result<Option<ModerationStatus>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let status: Option<String> = conn
+            .query_row(
+                "SELECT status FROM moderation WHERE content_hash = ?1",
+                [content_hash.0.to_vec()],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        status.as_deref().map(parse_status).transpose()
+    }
+
+    fn set_status(
+        &self,
+        content_hash: &Hash,
+        status: ModerationStatus,
+        updated_at: Timestamp,
+    ) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        conn.execute(
+            "INSERT INTO moderation (content_hash, status, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(content_hash) DO UPDATE SET status = ?2, updated_at = ?3",
+            params![content_hash.0.to_vec(), status.as_str(), updated_at as i64],
+        )?;
+
+        Ok(())
+    }
+
+    fn list(&self, status: Option<ModerationStatus>) -> Result<Vec<ModerationEntry>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT m.content_hash, m.status, m.updated_at,
+                    (SELECT COUNT(*) FROM content_reports r WHERE r.content_hash = m.content_hash)
+             FROM moderation m
+             WHERE ?1 IS NULL OR m.status = ?1
+             ORDER BY m.updated_at DESC",
+        )?;
+        let rows = stmt
+            .query_map([status.map(|s| s.as_str())], |row| {
+                Ok((
+                    row.get::<_, Vec<u8>>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        rows.into_iter()
+            .map(|(hash, status, updated_at, count)| {
+                Ok(ModerationEntry {
+                    content_hash: bytes_to_hash(&hash),
+                    status: parse_status(&status)?,
+                    report_count: count as u32,
+                    updated_at: updated_at as Timestamp,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_types::ReportReason;
+
+    fn setup_store() -> SqliteModerationStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteModerationStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    fn test_report(content: &[u8], created_at: Timestamp) -> ContentReport {
+        let (_, public_key) = generate_identity();
+        ContentReport::new(
+            content_hash(content),
+            peer_id_from_public_key(&public_key),
+            public_key,
+            ReportReason::Spam,
+            "",
+            created_at,
+        )
+    }
+
+    #[test]
+    fn test_store_report_enqueues() {
+        let store = setup_store();
+        let report = test_report(b"reported", 100);
+        let hash = report.content_hash;
+
+        assert!(store.get_status(&hash).unwrap().is_none());
+        assert!(store.store_report(&report, 100).unwrap());
+        assert_eq!(
+            store.get_status(&hash).unwrap(),
+            Some(ModerationStatus::Pending)
+        );
+
+        // The same reporter counts once
+        let mut again = report.clone();
+        again.reason = ReportReason::Abuse;
+        assert!(!store.store_report(&again, 200).unwrap());
+
+        let second = test_report(b"reported", 300);
+        assert!(store.store_report(&second, 300).unwrap());
+        assert_eq!(store.reports_for(&hash).unwrap(), vec![report, second]);
+    }
+
+    #[test]
+    fn test_status_and_queue() {
+        let store = setup_store();
+        let first = test_report(b"first", 100);
+        let second = test_report(b"second", 200);
+        store.store_report(&first, 100).unwrap();
+        store.store_report(&second, 200).unwrap();
+
+        let queue = store.list(None).unwrap();
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue[0].content_hash, second.content_hash);
+        assert_eq!(queue[0].report_count, 1);
+
+        store
+            .set_status(&first.content_hash, ModerationStatus::Hidden, 300)
+            .unwrap();
+        let hidden = store.list(Some(ModerationStatus::Hidden)).unwrap();
+        assert_eq!(hidden.len(), 1);
+        assert_eq!(hidden[0].content_hash, first.content_hash);
+        assert_eq!(hidden[0].updated_at, 300);
+
+        // Later reports don't reset a decision
+        store
+            .store_report(&test_report(b"first", 400), 400)
+            .unwrap();
+        assert_eq!(
+            store.get_status(&first.content_hash).unwrap(),
+            Some(ModerationStatus::Hidden)
+        );
+
+        // Content can be moderated without having been reported
+        let unreported = content_hash(b"unreported");
+        store
+            .set_status(&unreported, ModerationStatus::Hidden, 500)
+            .unwrap();
+        let entry = &store.list(Some(ModerationStatus::Hidden)).unwrap()[0];
+        assert_eq!(entry.content_hash, unreported);
+        assert_eq!(entry.report_count, 0);
+    }
+}
@@ -10,9 +10,12 @@ use nodalync_crypto::{PeerId, PublicKey, Timestamp};
 
 use crate::error::{Result, StoreError};
 use crate::traits::PeerStore;
-use crate::types::PeerInfo;
+use nodalync_wire::Capability;
+
+use crate::types::{LatencyStats, PeerInfo};
 
 /// SQLite-based peer store.
+#[derive(Clone)]
 pub struct SqlitePeerStore {
     conn: Arc<Mutex<Connection>>,
 }
@@ -30,8 +33,11 @@ impl SqlitePeerStore {
         let addresses_json: String = row.get(2)?;
         let last_seen: i64 = row.get(3)?;
         let reputation: i64 = row.get(4)?;
+        let capabilities: Option<u8> = row.get(5)?;
+        let latency_json: Option<String> = row.get(6)?;
 
         let addresses: Vec<String> = serde_json::from_str(&addresses_json).unwrap_or_default();
+        let latency = latency_json.and_then(|json| serde_json::from_str(&json).ok());
 
         Ok(PeerInfo {
             peer_id: bytes_to_peer_id(&peer_id_bytes),
@@ -39,12 +45,14 @@ impl SqlitePeerStore {
             addresses,
             last_seen: last_seen as Timestamp,
             reputation,
+            capabilities: capabilities.map(Capability::from_bits),
+            latency,
         })
     }
 }
 
 impl PeerStore for SqlitePeerStore {
-    fn upsert(&mut self, peer: &PeerInfo) -> Result<()> {
+    fn upsert(&self, peer: &PeerInfo) -> Result<()> {
         let conn = self
             .conn
             .lock()
@@ -55,23 +63,33 @@ impl PeerStore for SqlitePeerStore {
         let addresses_json = serde_json::to_string(&peer.addresses)?;
         let last_seen = peer.last_seen as i64;
         let reputation = peer.reputation;
-
-        conn.execute(
-            "INSERT INTO peers (peer_id, public_key, addresses, last_seen, reputation)
-             VALUES (?1, ?2, ?3, ?4, ?5)
+        let capabilities = peer.capabilities.as_deref().map(Capability::to_bits);
+        let latency_json = peer
+            .latency
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+
+        conn.prepare_cached(
+            "INSERT INTO peers (peer_id, public_key, addresses, last_seen, reputation, capabilities, latency)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
              ON CONFLICT(peer_id) DO UPDATE SET
                  public_key = excluded.public_key,
                  addresses = excluded.addresses,
                  last_seen = excluded.last_seen,
-                 reputation = excluded.reputation",
-            params![
-                peer_id_bytes,
-                public_key_bytes,
-                addresses_json,
-                last_seen,
-                reputation
-            ],
-        )?;
+                 reputation = excluded.reputation,
+                 capabilities = excluded.capabilities,
+                 latency = excluded.latency",
+        )?
+        .execute(params![
+            peer_id_bytes,
+            public_key_bytes,
+            addresses_json,
+            last_seen,
+            reputation,
+            capabilities,
+            latency_json
+        ])?;
 
         Ok(())
     }
@@ -84,12 +102,11 @@ impl PeerStore for SqlitePeerStore {
         let peer_id_bytes = peer_id.0.to_vec();
 
         let peer = conn
-            .query_row(
-                "SELECT peer_id, public_key, addresses, last_seen, reputation
+            .prepare_cached(
+                "SELECT peer_id, public_key, addresses, last_seen, reputation, capabilities, latency
                  FROM peers WHERE peer_id = ?1",
-                [peer_id_bytes],
-                Self::deserialize_peer,
-            )
+            )?
+            .query_row([peer_id_bytes], Self::deserialize_peer)
             .optional()?;
 
         Ok(peer)
@@ -102,7 +119,7 @@ impl PeerStore for SqlitePeerStore {
             .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
 
         let mut stmt = conn.prepare(
-            "SELECT peer_id, public_key, addresses, last_seen, reputation
+            "SELECT peer_id, public_key, addresses, last_seen, reputation, capabilities, latency
              FROM peers ORDER BY last_seen DESC",
         )?;
 
@@ -114,7 +131,7 @@ impl PeerStore for SqlitePeerStore {
         Ok(peers)
     }
 
-    fn update_last_seen(&mut self, peer_id: &PeerId, timestamp: Timestamp) -> Result<()> {
+    fn update_last_seen(&self, peer_id: &PeerId, timestamp: Timestamp) -> Result<()> {
         let conn = self
             .conn
             .lock()
@@ -133,7 +150,7 @@ impl PeerStore for SqlitePeerStore {
         Ok(())
     }
 
-    fn update_reputation(&mut self, peer_id: &PeerId, delta: i64) -> Result<()> {
+    fn update_reputation(&self, peer_id: &PeerId, delta: i64) -> Result<()> {
         let conn = self
             .conn
             .lock()
@@ -152,7 +169,40 @@ impl PeerStore for SqlitePeerStore {
         Ok(())
     }
 
-    fn delete(&mut self, peer_id: &PeerId) -> Result<()> {
+    fn record_latency(
+        &self,
+        peer_id: &PeerId,
+        rtt_ms: Option<u32>,
+        timestamp: Timestamp,
+    ) -> Result<LatencyStats> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+        let peer_id_bytes = peer_id.0.to_vec();
+
+        let latency_json: Option<String> = conn
+            .query_row(
+                "SELECT latency FROM peers WHERE peer_id = ?1",
+                [&peer_id_bytes],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or(StoreError::PeerNotFound)?;
+        let mut latency: LatencyStats = latency_json
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        latency.record(rtt_ms, timestamp);
+
+        conn.execute(
+            "UPDATE peers SET latency = ?2 WHERE peer_id = ?1",
+            params![peer_id_bytes, serde_json::to_string(&latency)?],
+        )?;
+
+        Ok(latency)
+    }
+
+    fn delete(&self, peer_id: &PeerId) -> Result<()> {
         let conn = self
             .conn
             .lock()
@@ -174,7 +224,7 @@ impl SqlitePeerStore {
             .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
 
         let mut stmt = conn.prepare(
-            "SELECT peer_id, public_key, addresses, last_seen, reputation
+            "SELECT peer_id, public_key, addresses, last_seen, reputation, capabilities, latency
              FROM peers WHERE reputation >= ?1 ORDER BY reputation DESC",
         )?;
 
@@ -194,7 +244,7 @@ impl SqlitePeerStore {
             .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
 
         let mut stmt = conn.prepare(
-            "SELECT peer_id, public_key, addresses, last_seen, reputation
+            "SELECT peer_id, public_key, addresses, last_seen, reputation, capabilities, latency
              FROM peers WHERE last_seen >= ?1 ORDER BY last_seen DESC",
         )?;
 
@@ -263,7 +313,7 @@ mod tests {
 
     #[test]
     fn test_upsert_and_get() {
-        let mut store = setup_store();
+        let store = setup_store();
         let peer = test_peer_info();
 
         store.upsert(&peer).unwrap();
@@ -277,11 +327,28 @@ mod tests {
         assert_eq!(loaded.addresses, peer.addresses);
         assert_eq!(loaded.last_seen, peer.last_seen);
         assert_eq!(loaded.reputation, 0);
+        assert_eq!(loaded.capabilities, None);
+    }
+
+    #[test]
+    fn test_capabilities_roundtrip() {
+        let store = setup_store();
+        let peer = test_peer_info()
+            .with_capabilities(vec![Capability::Query, Capability::ChunkedTransfer]);
+        store.upsert(&peer).unwrap();
+
+        let loaded = store.get(&peer.peer_id).unwrap().unwrap();
+        assert_eq!(loaded.capabilities, peer.capabilities);
+
+        // Advertising nothing is stored as an empty set, not as unknown
+        store.upsert(&peer.with_capabilities(vec![])).unwrap();
+        let loaded = store.get(&loaded.peer_id).unwrap().unwrap();
+        assert_eq!(loaded.capabilities, Some(vec![]));
     }
 
     #[test]
     fn test_upsert_updates_existing() {
-        let mut store = setup_store();
+        let store = setup_store();
         let mut peer = test_peer_info();
 
         store.upsert(&peer).unwrap();
@@ -296,6 +363,35 @@ mod tests {
         assert_eq!(loaded.reputation, 10);
     }
 
+    #[test]
+    fn test_record_latency() {
+        let store = setup_store();
+        let peer = test_peer_info();
+        store.upsert(&peer).unwrap();
+
+        store
+            .record_latency(&peer.peer_id, Some(40), 1_000)
+            .unwrap();
+        let stats = store.record_latency(&peer.peer_id, None, 2_000).unwrap();
+        assert_eq!(stats.samples, vec![Some(40), None]);
+        assert_eq!(stats.last_probe, 2_000);
+
+        let loaded = store.get(&peer.peer_id).unwrap().unwrap();
+        assert_eq!(loaded.latency, Some(stats));
+
+        // Upserting what was loaded keeps the statistics
+        store.upsert(&loaded).unwrap();
+        let reloaded = store.get(&peer.peer_id).unwrap().unwrap();
+        assert_eq!(reloaded.latency, loaded.latency);
+        assert!(reloaded.qos_score().is_some());
+
+        let (_, public_key) = generate_identity();
+        assert!(matches!(
+            store.record_latency(&peer_id_from_public_key(&public_key), Some(10), 0),
+            Err(StoreError::PeerNotFound)
+        ));
+    }
+
     #[test]
     fn test_get_nonexistent() {
         let store = setup_store();
@@ -308,7 +404,7 @@ mod tests {
 
     #[test]
     fn test_list() {
-        let mut store = setup_store();
+        let store = setup_store();
 
         let peer1 = test_peer_info();
         let peer2 = test_peer_info();
@@ -322,7 +418,7 @@ mod tests {
 
     #[test]
     fn test_update_last_seen() {
-        let mut store = setup_store();
+        let store = setup_store();
         let peer = test_peer_info();
 
         store.upsert(&peer).unwrap();
@@ -338,7 +434,7 @@ mod tests {
 
     #[test]
     fn test_update_last_seen_nonexistent() {
-        let mut store = setup_store();
+        let store = setup_store();
         let (_, public_key) = generate_identity();
         let peer_id = peer_id_from_public_key(&public_key);
 
@@ -348,7 +444,7 @@ mod tests {
 
     #[test]
     fn test_update_reputation() {
-        let mut store = setup_store();
+        let store = setup_store();
         let peer = test_peer_info();
 
         store.upsert(&peer).unwrap();
@@ -364,7 +460,7 @@ mod tests {
 
     #[test]
     fn test_delete() {
-        let mut store = setup_store();
+        let store = setup_store();
         let peer = test_peer_info();
 
         store.upsert(&peer).unwrap();
@@ -376,7 +472,7 @@ mod tests {
 
     #[test]
     fn test_delete_nonexistent() {
-        let mut store = setup_store();
+        let store = setup_store();
         let (_, public_key) = generate_identity();
         let peer_id = peer_id_from_public_key(&public_key);
 
@@ -386,7 +482,7 @@ mod tests {
 
     #[test]
     fn test_list_by_reputation() {
-        let mut store = setup_store();
+        let store = setup_store();
 
         let mut peer1 = test_peer_info();
         peer1.reputation = 5;
@@ -411,7 +507,7 @@ mod tests {
 
     #[test]
     fn test_list_recently_seen() {
-        let mut store = setup_store();
+        let store = setup_store();
 
         let mut peer1 = test_peer_info();
         peer1.last_seen = 1000;
@@ -432,7 +528,7 @@ mod tests {
 
     #[test]
     fn test_count() {
-        let mut store = setup_store();
+        let store = setup_store();
 
         assert_eq!(store.count().unwrap(), 0);
 
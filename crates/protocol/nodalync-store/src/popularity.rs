@@ -0,0 +1,274 @@
+//! Content popularity storage.
+//!
+//! This module counts previews, queries and search hits per content hash,
+//! whether the content is ours, cached, or only announced, and keeps a
+//! score that decays exponentially so recent demand outweighs old demand.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{Hash, Timestamp};
+
+use crate::error::{Result, StoreError};
+use crate::traits::PopularityStore;
+use crate::types::{decay, PopularityKind, PopularityRecord};
+
+/// SQLite-based popularity counters.
+pub struct SqlitePopularityStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqlitePopularityStore {
+    /// Create a new popularity store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+
+    fn deserialize_row(row: &rusqlite::Row) -> rusqlite::Result<PopularityRecord> {
+        let hash: Vec<u8> = row.get(0)?;
+        let previews: i64 = row.get(1)?;
+        let queries: i64 = row.get(2)?;
+        let search_hits: i64 = row.get(3)?;
+        let score: f64 = row.get(4)?;
+        let updated_at: i64 = row.get(5)?;
+        Ok(PopularityRecord {
+            hash: bytes_to_hash(&hash),
+            previews: previews as u64,
+            queries: queries as u64,
+            search_hits: search_hits as u64,
+            score,
+            updated_at: updated_at as Timestamp,
+        })
+    }
+}
+
+impl PopularityStore for SqlitePopularityStore {
+    fn record(
+        &self,
+        hash: &Hash,
+        kind: PopularityKind,
+        half_life_ms: u64,
+        timestamp: Timestamp,
+    ) -> Result<()> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+        let tx = conn.transaction()?;
+
+        let existing = tx
+            .prepare_cached(
+                "SELECT hash, previews, queries, search_hits, score, updated_at
+                 FROM popularity WHERE hash = ?1",
+            )?
+            .query_row([hash.0.to_vec()], Self::deserialize_row)
+            .optional()?;
+        let mut record = existing.unwrap_or(PopularityRecord {
+            hash: *hash,
+            previews: 0,
+            queries: 0,
+            search_hits: 0,
+            score: 0.0,
+            updated_at: timestamp,
+        });
+
+        match kind {
+            PopularityKind::Preview => record.previews += 1,
+            PopularityKind::Query => record.queries += 1,
+            PopularityKind::SearchHit => record.search_hits += 1,
+        }
+        // Requests counted out of order don't decay the score backwards
+        let updated_at = record.updated_at.max(timestamp);
+        record.score = decay(record.score, updated_at - record.updated_at, half_life_ms)
+            + decay(kind.weight(), updated_at - timestamp, half_life_ms);
+        record.updated_at = updated_at;
+
+        tx.execute(
+            "INSERT OR REPLACE INTO popularity (hash, previews, queries, search_hits, score, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                hash.0.to_vec(),
+                record.previews as i64,
+                record.queries as i64,
+                record.search_hits as i64,
+                record.score,
+                record.updated_at as i64,
+            ],
+        )?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn get(&self, hash: &Hash) -> Result<Option<PopularityRecord>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let record = conn
+            .query_row(
+                "SELECT hash, previews, queries, search_hits, score, updated_at
+                 FROM popularity WHERE hash = ?1",
+                [hash.0.to_vec()],
+                Self::deserialize_row,
+            )
+            .optional()?;
+
+        Ok(record)
+    }
+
+    fn top(
+        &self,
+        limit: usize,
+        half_life_ms: u64,
+        now: Timestamp,
+    ) -> Result<Vec<PopularityRecord>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT hash, previews, queries, search_hits, score, updated_at FROM popularity",
+        )?;
+        let mut records: Vec<PopularityRecord> = stmt
+            .query_map([], Self::deserialize_row)?
+            .filter_map(|r| r.ok())
+            .map(|mut record| {
+                record.score = record.score_at(now, half_life_ms);
+                record
+            })
+            .collect();
+
+        records.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.hash.0.cmp(&b.hash.0)));
+        records.truncate(limit);
+        Ok(records)
+    }
+
+    fn prune(&self, older_than: Timestamp) -> Result<u64> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let deleted = conn.execute(
+            "DELETE FROM popularity WHERE updated_at < ?1",
+            [older_than as i64],
+        )?;
+
+        Ok(deleted as u64)
+    }
+}
+
+/// Convert bytes to Hash.
+fn bytes_to_hash(bytes: &[u8]) -> Hash {
+    let mut arr = [0u8; 32];
+    if bytes.len() >= 32 {
+        arr.copy_from_slice(&bytes[..32]);
+    }
+    Hash(arr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::content_hash;
+
+    const HOUR: u64 = 3_600_000;
+
+    fn setup_store() -> SqlitePopularityStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqlitePopularityStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    #[test]
+    fn test_record_counts() {
+        let store = setup_store();
+        let hash = content_hash(b"content");
+        assert!(store.get(&hash).unwrap().is_none());
+
+        for kind in [
+            PopularityKind::Preview,
+            PopularityKind::Preview,
+            PopularityKind::Query,
+            PopularityKind::SearchHit,
+        ] {
+            store.record(&hash, kind, HOUR, 1000).unwrap();
+        }
+
+        let record = store.get(&hash).unwrap().unwrap();
+        assert_eq!(
+            (record.previews, record.queries, record.search_hits),
+            (2, 1, 1)
+        );
+        assert!((record.score - 5.25).abs() < 1e-9);
+        assert_eq!(record.updated_at, 1000);
+    }
+
+    #[test]
+    fn test_score_decays() {
+        let store = setup_store();
+        let hash = content_hash(b"content");
+        store
+            .record(&hash, PopularityKind::Query, HOUR, 1000)
+            .unwrap();
+
+        // Halves every half-life, when read and when the next request lands
+        let record = store.get(&hash).unwrap().unwrap();
+        assert!((record.score_at(1000 + HOUR, HOUR) - 1.5).abs() < 1e-9);
+        assert!((record.score_at(1000 + 2 * HOUR, HOUR) - 0.75).abs() < 1e-9);
+        assert_eq!(record.score_at(1000 + 2 * HOUR, 0), 3.0);
+
+        store
+            .record(&hash, PopularityKind::Preview, HOUR, 1000 + HOUR)
+            .unwrap();
+        let record = store.get(&hash).unwrap().unwrap();
+        assert!((record.score - 2.5).abs() < 1e-9);
+
+        // A late request doesn't move the clock back
+        store
+            .record(&hash, PopularityKind::Preview, HOUR, 1000)
+            .unwrap();
+        let record = store.get(&hash).unwrap().unwrap();
+        assert_eq!(record.updated_at, 1000 + HOUR);
+        assert!((record.score - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_top_and_prune() {
+        let store = setup_store();
+        let old = content_hash(b"old favourite");
+        let new = content_hash(b"new favourite");
+        let minor = content_hash(b"minor");
+
+        // Four queries a day ago lose to two queries now
+        for _ in 0..4 {
+            store.record(&old, PopularityKind::Query, HOUR, 0).unwrap();
+        }
+        for _ in 0..2 {
+            store
+                .record(&new, PopularityKind::Query, HOUR, 24 * HOUR)
+                .unwrap();
+        }
+        store
+            .record(&minor, PopularityKind::SearchHit, HOUR, 24 * HOUR)
+            .unwrap();
+
+        let top = store.top(2, HOUR, 24 * HOUR).unwrap();
+        assert_eq!(
+            top.iter().map(|r| r.hash).collect::<Vec<_>>(),
+            vec![new, minor]
+        );
+        assert!((top[0].score - 6.0).abs() < 1e-9);
+
+        // Without decay, the old favourite still leads
+        assert_eq!(store.top(1, 0, 24 * HOUR).unwrap()[0].hash, old);
+
+        assert_eq!(store.prune(HOUR).unwrap(), 1);
+        assert!(store.get(&old).unwrap().is_none());
+        assert!(store.get(&new).unwrap().is_some());
+    }
+}
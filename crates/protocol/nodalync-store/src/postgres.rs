@@ -0,0 +1,1313 @@
+//! Postgres-based shared stores.
+//!
+//! Several nodes (e.g. replicas of one publisher behind a load balancer) can
+//! share manifests, peers, payment channels and the settlement queue by
+//! pointing them at the same Postgres database with `database_url`. Every
+//! other store stays in the node's own SQLite database.
+//!
+//! The `postgres` client blocks on its own runtime, which panics inside a
+//! tokio runtime, so each [`PgDatabase`] owns its connection on a dedicated
+//! thread and stores send it jobs. Nodes coordinate through advisory locks:
+//! [`PgDatabase::try_advisory_lock`] elects a single node to settle the
+//! queue, and rows are written in single statements or row-locking
+//! transactions so concurrent nodes never interleave a read-modify-write.
+//!
+//! Records are kept as JSON next to the columns used for filtering. The
+//! in-memory manifest index and hot set are not used here, since another
+//! node may change a manifest at any time.
+
+use std::sync::mpsc;
+use std::thread;
+
+use postgres::types::ToSql;
+use postgres::{Client, NoTls};
+
+use nodalync_crypto::{Hash, PeerId, Timestamp};
+use nodalync_types::{
+    normalize_tag, Amount, CanonicalVersion, Channel, ChannelState, Manifest, Payment,
+};
+
+use crate::error::{Result, StoreError};
+use crate::manifest_index::ManifestIndexStats;
+use crate::traits::{ChannelStore, ManifestStore, PeerStore, SettlementQueueStore};
+use crate::types::{
+    ChannelCheckpoint, LatencyStats, ManifestFilter, PaymentDirection, PaymentNonces, PeerInfo,
+    QueuedDistribution, WalletTransaction, WalletTransactionKind,
+};
+
+/// Advisory lock key held by the node settling the shared queue.
+pub const SETTLEMENT_LOCK_KEY: i64 = 0x6e6f_6461_6c79_6e63;
+
+/// Statements creating the shared schema.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS manifests (
+        hash BYTEA PRIMARY KEY,
+        content_type SMALLINT NOT NULL,
+        owner BYTEA NOT NULL,
+        version_root BYTEA NOT NULL,
+        version_number BIGINT NOT NULL,
+        visibility SMALLINT NOT NULL,
+        title TEXT NOT NULL,
+        description TEXT,
+        tags TEXT[] NOT NULL,
+        price BIGINT NOT NULL,
+        created_at BIGINT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_manifests_version_root ON manifests(version_root);
+    CREATE INDEX IF NOT EXISTS idx_manifests_created_at ON manifests(created_at);
+    CREATE TABLE IF NOT EXISTS canonical_versions (
+        version_root BYTEA PRIMARY KEY,
+        data TEXT NOT NULL,
+        created_at BIGINT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS peers (
+        peer_id BYTEA PRIMARY KEY,
+        last_seen BIGINT NOT NULL,
+        reputation BIGINT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS channels (
+        peer_id BYTEA PRIMARY KEY,
+        state SMALLINT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS payments (
+        id BYTEA PRIMARY KEY,
+        channel_peer BYTEA NOT NULL,
+        timestamp BIGINT NOT NULL,
+        settled BOOLEAN NOT NULL DEFAULT FALSE,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_payments_channel_peer ON payments(channel_peer);
+    CREATE TABLE IF NOT EXISTS channel_checkpoints (
+        channel_id BYTEA NOT NULL,
+        nonce BIGINT NOT NULL,
+        peer_id BYTEA NOT NULL,
+        data TEXT NOT NULL,
+        PRIMARY KEY (channel_id, nonce)
+    );
+    CREATE TABLE IF NOT EXISTS payment_nonces (
+        channel_id BYTEA PRIMARY KEY,
+        peer_id BYTEA NOT NULL,
+        last_sent BIGINT NOT NULL,
+        last_received BIGINT NOT NULL,
+        updated_at BIGINT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS settlement_queue (
+        id BIGSERIAL PRIMARY KEY,
+        payment_id BYTEA NOT NULL,
+        recipient BYTEA NOT NULL,
+        amount BIGINT NOT NULL,
+        source_hash BYTEA NOT NULL,
+        queued_at BIGINT NOT NULL,
+        settled BOOLEAN NOT NULL DEFAULT FALSE,
+        batch_id BYTEA
+    );
+    CREATE INDEX IF NOT EXISTS idx_settlement_queue_settled ON settlement_queue(settled);
+    CREATE TABLE IF NOT EXISTS settlement_meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS wallet_transactions (
+        transaction_id TEXT PRIMARY KEY,
+        kind TEXT NOT NULL,
+        amount BIGINT NOT NULL,
+        timestamp BIGINT NOT NULL
+    );
+";
+
+/// Work sent to the connection thread.
+type Job = Box<dyn FnOnce(&mut Client) + Send>;
+
+/// Handle to a shared Postgres database.
+///
+/// Cheap to clone; all clones share one connection, which is re-opened if
+/// the server drops it.
+#[derive(Clone)]
+pub struct PgDatabase {
+    jobs: mpsc::Sender<Job>,
+}
+
+impl PgDatabase {
+    /// Connect to `url` and create the shared schema if needed.
+    pub fn connect(url: &str) -> Result<Self> {
+        let (jobs, receiver) = mpsc::channel::<Job>();
+        let (ready, ready_receiver) = mpsc::channel();
+        let url = url.to_string();
+
+        thread::Builder::new()
+            .name("nodalync-postgres".to_string())
+            .spawn(move || {
+                let mut client = match open(&url) {
+                    Ok(client) => client,
+                    Err(e) => {
+                        let _ = ready.send(Err(e));
+                        return;
+                    }
+                };
+                let _ = ready.send(Ok(()));
+
+                for job in receiver {
+                    if client.is_closed() {
+                        match open(&url) {
+                            Ok(reopened) => client = reopened,
+                            Err(e) => {
+                                tracing::warn!(error = %e, "Failed to reconnect to Postgres");
+                            }
+                        }
+                    }
+                    job(&mut client);
+                }
+            })?;
+
+        ready_receiver
+            .recv()
+            .map_err(|_| StoreError::postgres("connection thread exited"))??;
+        tracing::info!("Connected to shared Postgres database");
+
+        Ok(Self { jobs })
+    }
+
+    /// Run `f` on the connection thread and wait for its result.
+    pub fn run<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Client) -> Result<T> + Send + 'static,
+    {
+        let (reply, receiver) = mpsc::sync_channel(1);
+        self.jobs
+            .send(Box::new(move |client| {
+                let _ = reply.send(f(client));
+            }))
+            .map_err(|_| StoreError::postgres("connection thread exited"))?;
+        receiver
+            .recv()
+            .map_err(|_| StoreError::postgres("connection thread exited"))?
+    }
+
+    /// Try to take a session advisory lock without waiting.
+    ///
+    /// Returns `None` if another node holds it. The lock is released when
+    /// the returned guard is dropped, or by the server if the connection
+    /// is lost.
+    pub fn try_advisory_lock(&self, key: i64) -> Result<Option<AdvisoryLock>> {
+        let acquired = self.run(move |client| {
+            let row = client.query_one("SELECT pg_try_advisory_lock($1)", &[&key])?;
+            Ok(row.get::<_, bool>(0))
+        })?;
+
+        Ok(acquired.then(|| AdvisoryLock {
+            db: self.clone(),
+            key,
+        }))
+    }
+}
+
+/// Open a connection and create the schema.
+fn open(url: &str) -> Result<Client> {
+    let mut client = Client::connect(url, NoTls)?;
+    client.batch_execute(SCHEMA)?;
+    Ok(client)
+}
+
+/// A held session advisory lock, released on drop.
+pub struct AdvisoryLock {
+    db: PgDatabase,
+    key: i64,
+}
+
+impl Drop for AdvisoryLock {
+    fn drop(&mut self) {
+        let key = self.key;
+        // Released in the background so dropping never blocks
+        let _ = self.db.jobs.send(Box::new(move |client| {
+            if let Err(e) = client.execute("SELECT pg_advisory_unlock($1)", &[&key]) {
+                tracing::warn!(key, error = %e, "Failed to release advisory lock");
+            }
+        }));
+    }
+}
+
+/// Query parameters built up at runtime.
+type Params = Vec<Box<dyn ToSql + Sync + Send>>;
+
+/// Postgres-based manifest store.
+#[derive(Clone)]
+pub struct PgManifestStore {
+    db: PgDatabase,
+}
+
+impl PgManifestStore {
+    /// Create a manifest store on a shared database.
+    pub fn new(db: PgDatabase) -> Self {
+        Self { db }
+    }
+
+    /// No-op: shared manifests are not indexed in memory.
+    pub fn set_index_capacity(&self, _max_bytes: usize) {}
+
+    /// Counters of the manifest index, which is unused here.
+    pub fn index_stats(&self) -> ManifestIndexStats {
+        ManifestIndexStats::default()
+    }
+
+    /// No-op: shared manifests are not held in memory. Returns 0.
+    pub fn retain_hot(&self, _hashes: &[Hash]) -> Result<usize> {
+        Ok(0)
+    }
+
+    /// Always false: shared manifests are not held in memory.
+    pub fn is_hot(&self, _hash: &Hash) -> bool {
+        false
+    }
+
+    /// Insert a manifest unless one with its hash exists, returning whether
+    /// it was inserted.
+    fn insert(client: &mut impl postgres::GenericClient, manifest: &Manifest) -> Result<bool> {
+        let tags = manifest_tags(manifest);
+        let inserted = client.execute(
+            "INSERT INTO manifests (hash, content_type, owner, version_root, version_number,
+                 visibility, title, description, tags, price, created_at, data)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+             ON CONFLICT (hash) DO NOTHING",
+            &[
+                &manifest.hash.0.as_slice(),
+                &(manifest.content_type as u8 as i16),
+                &manifest.owner.0.as_slice(),
+                &manifest.version.root.0.as_slice(),
+                &(manifest.version.number as i64),
+                &(manifest.visibility as u8 as i16),
+                &manifest.metadata.title,
+                &manifest.metadata.description,
+                &tags,
+                &(manifest.economics.price as i64),
+                &(manifest.created_at as i64),
+                &serde_json::to_string(manifest)?,
+            ],
+        )?;
+        Ok(inserted > 0)
+    }
+}
+
+impl ManifestStore for PgManifestStore {
+    fn store(&self, manifest: &Manifest) -> Result<()> {
+        let manifest = manifest.clone();
+        self.db.run(move |client| Self::insert(client, &manifest))?;
+        Ok(())
+    }
+
+    fn store_batch(&self, manifests: &[Manifest]) -> Result<usize> {
+        let manifests = manifests.to_vec();
+        self.db.run(move |client| {
+            let mut tx = client.transaction()?;
+            let mut inserted = 0;
+            for manifest in &manifests {
+                if Self::insert(&mut tx, manifest)? {
+                    inserted += 1;
+                }
+            }
+            tx.commit()?;
+            Ok(inserted)
+        })
+    }
+
+    fn load(&self, hash: &Hash) -> Result<Option<Manifest>> {
+        let hash = *hash;
+        self.db.run(move |client| {
+            let row = client.query_opt(
+                "SELECT data FROM manifests WHERE hash = $1",
+                &[&hash.0.as_slice()],
+            )?;
+            Ok(row
+                .map(|row| serde_json::from_str(row.get(0)))
+                .transpose()?)
+        })
+    }
+
+    fn update(&self, manifest: &Manifest) -> Result<()> {
+        let mut manifest = manifest.clone();
+        self.db.run(move |client| {
+            let mut tx = client.transaction()?;
+            // Lock the row so a concurrent update on another node waits
+            let Some(row) = tx.query_opt(
+                "SELECT created_at FROM manifests WHERE hash = $1 FOR UPDATE",
+                &[&manifest.hash.0.as_slice()],
+            )?
+            else {
+                return Err(StoreError::ManifestNotFound(manifest.hash));
+            };
+            // Don't update created_at
+            manifest.created_at = row.get::<_, i64>(0) as Timestamp;
+
+            let tags = manifest_tags(&manifest);
+            tx.execute(
+                "UPDATE manifests SET
+                    content_type = $2, owner = $3, version_root = $4, version_number = $5,
+                    visibility = $6, title = $7, description = $8, tags = $9, price = $10,
+                    data = $11
+                 WHERE hash = $1",
+                &[
+                    &manifest.hash.0.as_slice(),
+                    &(manifest.content_type as u8 as i16),
+                    &manifest.owner.0.as_slice(),
+                    &manifest.version.root.0.as_slice(),
+                    &(manifest.version.number as i64),
+                    &(manifest.visibility as u8 as i16),
+                    &manifest.metadata.title,
+                    &manifest.metadata.description,
+                    &tags,
+                    &(manifest.economics.price as i64),
+                    &serde_json::to_string(&manifest)?,
+                ],
+            )?;
+            tx.commit()?;
+            Ok(())
+        })
+    }
+
+    fn delete(&self, hash: &Hash) -> Result<()> {
+        let hash = *hash;
+        self.db.run(move |client| {
+            client.execute(
+                "DELETE FROM manifests WHERE hash = $1",
+                &[&hash.0.as_slice()],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn list(&self, filter: ManifestFilter) -> Result<Vec<Manifest>> {
+        let mut sql = String::from("SELECT data FROM manifests WHERE TRUE");
+        let mut params: Params = Vec::new();
+
+        if let Some(visibility) = filter.visibility {
+            params.push(Box::new(visibility as u8 as i16));
+            sql.push_str(&format!(" AND visibility = ${}", params.len()));
+        }
+
+        if let Some(content_type) = filter.content_type {
+            params.push(Box::new(content_type as u8 as i16));
+            sql.push_str(&format!(" AND content_type = ${}", params.len()));
+        }
+
+        if let Some(created_after) = filter.created_after {
+            params.push(Box::new(created_after as i64));
+            sql.push_str(&format!(" AND created_at >= ${}", params.len()));
+        }
+
+        if let Some(created_before) = filter.created_before {
+            params.push(Box::new(created_before as i64));
+            sql.push_str(&format!(" AND created_at <= ${}", params.len()));
+        }
+
+        if let Some(owner) = filter.owner {
+            params.push(Box::new(owner.0.to_vec()));
+            sql.push_str(&format!(" AND owner = ${}", params.len()));
+        }
+
+        if let Some(ref content_types) = filter.content_types {
+            // An empty list matches nothing
+            let codes: Vec<i16> = content_types.iter().map(|ct| *ct as u8 as i16).collect();
+            params.push(Box::new(codes));
+            sql.push_str(&format!(" AND content_type = ANY(${})", params.len()));
+        }
+
+        if let Some(min_price) = filter.min_price {
+            params.push(Box::new(min_price as i64));
+            sql.push_str(&format!(" AND price >= ${}", params.len()));
+        }
+
+        if let Some(max_price) = filter.max_price {
+            params.push(Box::new(max_price as i64));
+            sql.push_str(&format!(" AND price <= ${}", params.len()));
+        }
+
+        if let Some(ref tags) = filter.tags {
+            for tag in tags {
+                params.push(Box::new(
+                    normalize_tag(tag).unwrap_or_else(|| tag.to_lowercase()),
+                ));
+                // A tag also matches its descendants (`science` matches `science/biology`)
+                sql.push_str(&format!(
+                    " AND EXISTS (SELECT 1 FROM unnest(tags) AS t WHERE t = ${0} OR left(t, length(${0}) + 1) = ${0} || '/')",
+                    params.len()
+                ));
+            }
+        }
+
+        if let Some(ref text_query) = filter.text_query {
+            params.push(Box::new(format!("%{}%", text_query.to_lowercase())));
+            sql.push_str(&format!(
+                " AND (LOWER(title) LIKE ${0} OR LOWER(COALESCE(description, '')) LIKE ${0} OR array_to_string(tags, ' ') LIKE ${0})",
+                params.len()
+            ));
+        }
+
+        sql.push_str(" ORDER BY created_at DESC");
+
+        if let Some(limit) = filter.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        if let Some(offset) = filter.offset {
+            sql.push_str(&format!(" OFFSET {}", offset));
+        }
+
+        self.db.run(move |client| {
+            let params: Vec<&(dyn ToSql + Sync)> = params
+                .iter()
+                .map(|p| p.as_ref() as &(dyn ToSql + Sync))
+                .collect();
+            let rows = client.query(sql.as_str(), &params)?;
+            Ok(rows
+                .iter()
+                .filter_map(|row| serde_json::from_str(row.get(0)).ok())
+                .collect())
+        })
+    }
+
+    fn get_versions(&self, version_root: &Hash) -> Result<Vec<Manifest>> {
+        let root = *version_root;
+        self.db.run(move |client| {
+            let rows = client.query(
+                "SELECT data FROM manifests WHERE version_root = $1 ORDER BY version_number ASC",
+                &[&root.0.as_slice()],
+            )?;
+            Ok(rows
+                .iter()
+                .filter_map(|row| serde_json::from_str(row.get(0)).ok())
+                .collect())
+        })
+    }
+
+    fn set_canonical(&self, pointer: &CanonicalVersion) -> Result<bool> {
+        let root = pointer.root;
+        let created_at = pointer.created_at as i64;
+        let data = serde_json::to_string(pointer)?;
+        self.db.run(move |client| {
+            let stored = client.execute(
+                "INSERT INTO canonical_versions (version_root, data, created_at)
+                 VALUES ($1, $2, $3)
+                 ON CONFLICT (version_root) DO UPDATE SET
+                    data = excluded.data, created_at = excluded.created_at
+                 WHERE excluded.created_at > canonical_versions.created_at",
+                &[&root.0.as_slice(), &data, &created_at],
+            )?;
+            Ok(stored > 0)
+        })
+    }
+
+    fn get_canonical(&self, version_root: &Hash) -> Result<Option<CanonicalVersion>> {
+        let root = *version_root;
+        self.db.run(move |client| {
+            let row = client.query_opt(
+                "SELECT data FROM canonical_versions WHERE version_root = $1",
+                &[&root.0.as_slice()],
+            )?;
+            Ok(row
+                .map(|row| serde_json::from_str(row.get(0)))
+                .transpose()?)
+        })
+    }
+}
+
+/// Lowercased tags of a manifest, as matched by tag and text filters.
+fn manifest_tags(manifest: &Manifest) -> Vec<String> {
+    manifest
+        .metadata
+        .tags
+        .iter()
+        .map(|tag| tag.to_lowercase())
+        .collect()
+}
+
+/// Postgres-based peer store.
+#[derive(Clone)]
+pub struct PgPeerStore {
+    db: PgDatabase,
+}
+
+impl PgPeerStore {
+    /// Create a peer store on a shared database.
+    pub fn new(db: PgDatabase) -> Self {
+        Self { db }
+    }
+
+    /// Deserialize a peer from `data`, `last_seen` and `reputation` columns.
+    fn deserialize_peer(row: &postgres::Row) -> Option<PeerInfo> {
+        let mut peer: PeerInfo = serde_json::from_str(row.get(0)).ok()?;
+        peer.last_seen = row.get::<_, i64>(1) as Timestamp;
+        peer.reputation = row.get(2);
+        Some(peer)
+    }
+}
+
+impl PeerStore for PgPeerStore {
+    fn upsert(&self, peer: &PeerInfo) -> Result<()> {
+        let peer_id = peer.peer_id;
+        let last_seen = peer.last_seen as i64;
+        let reputation = peer.reputation;
+        let data = serde_json::to_string(peer)?;
+        self.db.run(move |client| {
+            client.execute(
+                "INSERT INTO peers (peer_id, last_seen, reputation, data)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (peer_id) DO UPDATE SET
+                     last_seen = excluded.last_seen,
+                     reputation = excluded.reputation,
+                     data = excluded.data",
+                &[&peer_id.0.as_slice(), &last_seen, &reputation, &data],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn get(&self, peer_id: &PeerId) -> Result<Option<PeerInfo>> {
+        let peer_id = *peer_id;
+        self.db.run(move |client| {
+            let row = client.query_opt(
+                "SELECT data, last_seen, reputation FROM peers WHERE peer_id = $1",
+                &[&peer_id.0.as_slice()],
+            )?;
+            Ok(row.as_ref().and_then(Self::deserialize_peer))
+        })
+    }
+
+    fn list(&self) -> Result<Vec<PeerInfo>> {
+        self.db.run(|client| {
+            let rows = client.query(
+                "SELECT data, last_seen, reputation FROM peers ORDER BY last_seen DESC",
+                &[],
+            )?;
+            Ok(rows.iter().filter_map(Self::deserialize_peer).collect())
+        })
+    }
+
+    fn update_last_seen(&self, peer_id: &PeerId, timestamp: Timestamp) -> Result<()> {
+        let peer_id = *peer_id;
+        self.db.run(move |client| {
+            let updated = client.execute(
+                "UPDATE peers SET last_seen = $2 WHERE peer_id = $1",
+                &[&peer_id.0.as_slice(), &(timestamp as i64)],
+            )?;
+            if updated == 0 {
+                return Err(StoreError::PeerNotFound);
+            }
+            Ok(())
+        })
+    }
+
+    fn update_reputation(&self, peer_id: &PeerId, delta: i64) -> Result<()> {
+        let peer_id = *peer_id;
+        self.db.run(move |client| {
+            let updated = client.execute(
+                "UPDATE peers SET reputation = reputation + $2 WHERE peer_id = $1",
+                &[&peer_id.0.as_slice(), &delta],
+            )?;
+            if updated == 0 {
+                return Err(StoreError::PeerNotFound);
+            }
+            Ok(())
+        })
+    }
+
+    fn record_latency(
+        &self,
+        peer_id: &PeerId,
+        rtt_ms: Option<u32>,
+        timestamp: Timestamp,
+    ) -> Result<LatencyStats> {
+        let peer_id = *peer_id;
+        self.db.run(move |client| {
+            let mut tx = client.transaction()?;
+            // Lock the row so a concurrent probe from another node waits
+            let Some(row) = tx.query_opt(
+                "SELECT data FROM peers WHERE peer_id = $1 FOR UPDATE",
+                &[&peer_id.0.as_slice()],
+            )?
+            else {
+                return Err(StoreError::PeerNotFound);
+            };
+            let mut peer: PeerInfo = serde_json::from_str(row.get(0))?;
+            let latency = peer.latency.get_or_insert_with(LatencyStats::default);
+            latency.record(rtt_ms, timestamp);
+            let latency = latency.clone();
+            tx.execute(
+                "UPDATE peers SET data = $2 WHERE peer_id = $1",
+                &[&peer_id.0.as_slice(), &serde_json::to_string(&peer)?],
+            )?;
+            tx.commit()?;
+            Ok(latency)
+        })
+    }
+
+    fn delete(&self, peer_id: &PeerId) -> Result<()> {
+        let peer_id = *peer_id;
+        self.db.run(move |client| {
+            client.execute(
+                "DELETE FROM peers WHERE peer_id = $1",
+                &[&peer_id.0.as_slice()],
+            )?;
+            Ok(())
+        })
+    }
+}
+
+/// Postgres-based channel store.
+#[derive(Clone)]
+pub struct PgChannelStore {
+    db: PgDatabase,
+}
+
+impl PgChannelStore {
+    /// Create a channel store on a shared database.
+    pub fn new(db: PgDatabase) -> Self {
+        Self { db }
+    }
+
+    /// Serialize a channel without its pending payments, which are kept in
+    /// the payments table.
+    fn serialize_channel(channel: &Channel) -> Result<String> {
+        let mut channel = channel.clone();
+        channel.pending_payments.clear();
+        Ok(serde_json::to_string(&channel)?)
+    }
+
+    /// Load pending (unsettled) payments for a peer.
+    fn load_pending_payments(client: &mut Client, peer: &PeerId) -> Result<Vec<Payment>> {
+        let rows = client.query(
+            "SELECT data FROM payments WHERE channel_peer = $1 AND NOT settled
+             ORDER BY timestamp ASC",
+            &[&peer.0.as_slice()],
+        )?;
+        Ok(rows
+            .iter()
+            .filter_map(|row| serde_json::from_str(row.get(0)).ok())
+            .collect())
+    }
+
+    /// Delete a channel and all its payments.
+    pub fn delete(&self, peer: &PeerId) -> Result<()> {
+        let peer = *peer;
+        self.db.run(move |client| {
+            let peer_bytes = peer.0.as_slice();
+            let mut tx = client.transaction()?;
+            tx.execute(
+                "DELETE FROM payments WHERE channel_peer = $1",
+                &[&peer_bytes],
+            )?;
+            tx.execute(
+                "DELETE FROM channel_checkpoints WHERE peer_id = $1",
+                &[&peer_bytes],
+            )?;
+            tx.execute(
+                "DELETE FROM payment_nonces WHERE peer_id = $1",
+                &[&peer_bytes],
+            )?;
+            tx.execute("DELETE FROM channels WHERE peer_id = $1", &[&peer_bytes])?;
+            tx.commit()?;
+            Ok(())
+        })
+    }
+
+    /// Count channels that are not yet closed.
+    pub fn count_active(&self) -> Result<usize> {
+        self.db.run(|client| {
+            let row = client.query_one(
+                "SELECT COUNT(*) FROM channels WHERE state != $1",
+                &[&(ChannelState::Closed as u8 as i16)],
+            )?;
+            Ok(row.get::<_, i64>(0) as usize)
+        })
+    }
+
+    /// Record a signed channel state. Re-recording the same nonce
+    /// overwrites the previous checkpoint.
+    pub fn checkpoint(&self, checkpoint: &ChannelCheckpoint) -> Result<()> {
+        let channel_id = checkpoint.channel_id;
+        let nonce = checkpoint.nonce as i64;
+        let peer_id = checkpoint.peer_id;
+        let data = serde_json::to_string(checkpoint)?;
+        self.db.run(move |client| {
+            client.execute(
+                "INSERT INTO channel_checkpoints (channel_id, nonce, peer_id, data)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (channel_id, nonce) DO UPDATE SET
+                     peer_id = excluded.peer_id, data = excluded.data",
+                &[
+                    &channel_id.0.as_slice(),
+                    &nonce,
+                    &peer_id.0.as_slice(),
+                    &data,
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Get the checkpoint with the highest nonce for a channel.
+    pub fn latest_checkpoint(&self, channel_id: &Hash) -> Result<Option<ChannelCheckpoint>> {
+        let channel_id = *channel_id;
+        self.db.run(move |client| {
+            let row = client.query_opt(
+                "SELECT data FROM channel_checkpoints WHERE channel_id = $1
+                 ORDER BY nonce DESC LIMIT 1",
+                &[&channel_id.0.as_slice()],
+            )?;
+            Ok(row
+                .map(|row| serde_json::from_str(row.get(0)))
+                .transpose()?)
+        })
+    }
+
+    /// List all checkpoints for a channel, ordered by nonce.
+    pub fn checkpoints(&self, channel_id: &Hash) -> Result<Vec<ChannelCheckpoint>> {
+        let channel_id = *channel_id;
+        self.db.run(move |client| {
+            let rows = client.query(
+                "SELECT data FROM channel_checkpoints WHERE channel_id = $1 ORDER BY nonce ASC",
+                &[&channel_id.0.as_slice()],
+            )?;
+            rows.iter()
+                .map(|row| Ok(serde_json::from_str(row.get(0))?))
+                .collect()
+        })
+    }
+
+    /// Find nonce ranges missing between consecutive checkpoints.
+    pub fn checkpoint_gaps(&self, channel_id: &Hash) -> Result<Vec<(u64, u64)>> {
+        let checkpoints = self.checkpoints(channel_id)?;
+
+        let gaps = checkpoints
+            .windows(2)
+            .filter(|pair| pair[1].nonce > pair[0].nonce + 1)
+            .map(|pair| (pair[0].nonce + 1, pair[1].nonce - 1))
+            .collect();
+
+        Ok(gaps)
+    }
+
+    /// Delete checkpoints for a channel with a nonce below `nonce`.
+    pub fn prune_checkpoints(&self, channel_id: &Hash, nonce: u64) -> Result<usize> {
+        let channel_id = *channel_id;
+        self.db.run(move |client| {
+            let deleted = client.execute(
+                "DELETE FROM channel_checkpoints WHERE channel_id = $1 AND nonce < $2",
+                &[&channel_id.0.as_slice(), &(nonce as i64)],
+            )?;
+            Ok(deleted as usize)
+        })
+    }
+
+    /// Record a payment nonce used on a channel.
+    ///
+    /// Fails with [`StoreError::StaleNonce`] unless the nonce is above the
+    /// highest recorded in its direction by any node sharing the database.
+    pub fn record_payment_nonce(
+        &self,
+        channel_id: &Hash,
+        peer: &PeerId,
+        direction: PaymentDirection,
+        nonce: u64,
+        timestamp: Timestamp,
+    ) -> Result<()> {
+        let channel_id = *channel_id;
+        let peer = *peer;
+        let column = match direction {
+            PaymentDirection::Sent => "last_sent",
+            PaymentDirection::Received => "last_received",
+        };
+        self.db.run(move |client| {
+            client.execute(
+                "INSERT INTO payment_nonces
+                 (channel_id, peer_id, last_sent, last_received, updated_at)
+                 VALUES ($1, $2, 0, 0, $3)
+                 ON CONFLICT (channel_id) DO NOTHING",
+                &[
+                    &channel_id.0.as_slice(),
+                    &peer.0.as_slice(),
+                    &(timestamp as i64),
+                ],
+            )?;
+
+            // The comparison and update are one statement, so two nodes
+            // cannot both claim a nonce
+            let updated = client.execute(
+                format!(
+                    "UPDATE payment_nonces SET {column} = $1, updated_at = $2
+                     WHERE channel_id = $3 AND {column} < $1"
+                )
+                .as_str(),
+                &[
+                    &(nonce as i64),
+                    &(timestamp as i64),
+                    &channel_id.0.as_slice(),
+                ],
+            )?;
+
+            if updated == 0 {
+                let row = client.query_one(
+                    format!("SELECT {column} FROM payment_nonces WHERE channel_id = $1").as_str(),
+                    &[&channel_id.0.as_slice()],
+                )?;
+                return Err(StoreError::StaleNonce {
+                    nonce,
+                    last: row.get::<_, i64>(0) as u64,
+                });
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Get the highest payment nonces recorded for a channel.
+    pub fn payment_nonces(&self, channel_id: &Hash) -> Result<Option<PaymentNonces>> {
+        let channel_id = *channel_id;
+        self.db.run(move |client| {
+            let row = client.query_opt(
+                "SELECT peer_id, last_sent, last_received, updated_at
+                 FROM payment_nonces WHERE channel_id = $1",
+                &[&channel_id.0.as_slice()],
+            )?;
+            Ok(row.map(|row| PaymentNonces {
+                channel_id,
+                peer_id: bytes_to_peer_id(row.get(0)),
+                last_sent: row.get::<_, i64>(1) as u64,
+                last_received: row.get::<_, i64>(2) as u64,
+                updated_at: row.get::<_, i64>(3) as Timestamp,
+            }))
+        })
+    }
+}
+
+impl ChannelStore for PgChannelStore {
+    fn create(&self, peer: &PeerId, channel: Channel) -> Result<()> {
+        let peer = *peer;
+        let state = channel.state as u8 as i16;
+        let data = Self::serialize_channel(&channel)?;
+        self.db.run(move |client| {
+            let inserted = client.execute(
+                "INSERT INTO channels (peer_id, state, data) VALUES ($1, $2, $3)
+                 ON CONFLICT (peer_id) DO NOTHING",
+                &[&peer.0.as_slice(), &state, &data],
+            )?;
+            if inserted == 0 {
+                return Err(StoreError::invalid_data("Channel already exists for peer"));
+            }
+            Ok(())
+        })
+    }
+
+    fn get(&self, peer: &PeerId) -> Result<Option<Channel>> {
+        let peer = *peer;
+        self.db.run(move |client| {
+            let Some(row) = client.query_opt(
+                "SELECT data FROM channels WHERE peer_id = $1",
+                &[&peer.0.as_slice()],
+            )?
+            else {
+                return Ok(None);
+            };
+            let mut channel: Channel = serde_json::from_str(row.get(0))?;
+            channel.pending_payments = Self::load_pending_payments(client, &peer)?;
+            Ok(Some(channel))
+        })
+    }
+
+    fn update(&self, peer: &PeerId, channel: &Channel) -> Result<()> {
+        let peer = *peer;
+        let state = channel.state as u8 as i16;
+        let data = Self::serialize_channel(channel)?;
+        self.db.run(move |client| {
+            let updated = client.execute(
+                "UPDATE channels SET state = $2, data = $3 WHERE peer_id = $1",
+                &[&peer.0.as_slice(), &state, &data],
+            )?;
+            if updated == 0 {
+                return Err(StoreError::ChannelNotFound);
+            }
+            Ok(())
+        })
+    }
+
+    fn list_open(&self) -> Result<Vec<(PeerId, Channel)>> {
+        self.db.run(|client| {
+            let rows = client.query(
+                "SELECT peer_id, data FROM channels WHERE state = $1",
+                &[&(ChannelState::Open as u8 as i16)],
+            )?;
+
+            let mut channels = Vec::with_capacity(rows.len());
+            for row in &rows {
+                let peer_id = bytes_to_peer_id(row.get(0));
+                let Ok(mut channel) = serde_json::from_str::<Channel>(row.get(1)) else {
+                    continue;
+                };
+                if let Ok(payments) = Self::load_pending_payments(client, &peer_id) {
+                    channel.pending_payments = payments;
+                }
+                channels.push((peer_id, channel));
+            }
+            Ok(channels)
+        })
+    }
+
+    fn clear_all(&self) -> Result<()> {
+        self.db.run(|client| {
+            client.batch_execute(
+                "DELETE FROM channel_checkpoints;
+                 DELETE FROM payment_nonces;
+                 DELETE FROM channels;",
+            )?;
+            Ok(())
+        })
+    }
+
+    fn add_payment(&self, peer: &PeerId, payment: Payment) -> Result<()> {
+        let peer = *peer;
+        let data = serde_json::to_string(&payment)?;
+        self.db.run(move |client| {
+            client.execute(
+                "INSERT INTO payments (id, channel_peer, timestamp, settled, data)
+                 VALUES ($1, $2, $3, FALSE, $4)",
+                &[
+                    &payment.id.0.as_slice(),
+                    &peer.0.as_slice(),
+                    &(payment.timestamp as i64),
+                    &data,
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn get_pending_payments(&self, peer: &PeerId) -> Result<Vec<Payment>> {
+        let peer = *peer;
+        self.db
+            .run(move |client| Self::load_pending_payments(client, &peer))
+    }
+
+    fn clear_payments(&self, peer: &PeerId, payment_ids: &[Hash]) -> Result<()> {
+        let peer = *peer;
+        let ids: Vec<Vec<u8>> = payment_ids.iter().map(|id| id.0.to_vec()).collect();
+        self.db.run(move |client| {
+            client.execute(
+                "UPDATE payments SET settled = TRUE WHERE channel_peer = $1 AND id = ANY($2)",
+                &[&peer.0.as_slice(), &ids],
+            )?;
+            Ok(())
+        })
+    }
+}
+
+/// Postgres-based settlement queue.
+#[derive(Clone)]
+pub struct PgSettlementQueue {
+    db: PgDatabase,
+}
+
+impl PgSettlementQueue {
+    /// Create a settlement queue on a shared database.
+    pub fn new(db: PgDatabase) -> Self {
+        Self { db }
+    }
+
+    /// Take the settlement lock, unless another node holds it.
+    pub fn try_lock_batch(&self) -> Result<Option<AdvisoryLock>> {
+        self.db.try_advisory_lock(SETTLEMENT_LOCK_KEY)
+    }
+
+    /// Query pending distributions, optionally for one recipient.
+    fn query_pending(&self, recipient: Option<PeerId>) -> Result<Vec<QueuedDistribution>> {
+        self.db.run(move |client| {
+            let rows = client.query(
+                "SELECT payment_id, recipient, amount, source_hash, queued_at
+                 FROM settlement_queue
+                 WHERE NOT settled AND ($1::BYTEA IS NULL OR recipient = $1)
+                 ORDER BY queued_at ASC, id ASC",
+                &[&recipient.as_ref().map(|r| r.0.as_slice())],
+            )?;
+            Ok(rows
+                .iter()
+                .map(|row| QueuedDistribution {
+                    payment_id: bytes_to_hash(row.get(0)),
+                    recipient: bytes_to_peer_id(row.get(1)),
+                    amount: row.get::<_, i64>(2) as Amount,
+                    source_hash: bytes_to_hash(row.get(3)),
+                    queued_at: row.get::<_, i64>(4) as Timestamp,
+                })
+                .collect())
+        })
+    }
+
+    /// Get count of pending distributions.
+    pub fn pending_count(&self) -> Result<u64> {
+        self.db.run(|client| {
+            let row = client.query_one(
+                "SELECT COUNT(*) FROM settlement_queue WHERE NOT settled",
+                &[],
+            )?;
+            Ok(row.get::<_, i64>(0) as u64)
+        })
+    }
+
+    /// Record an on-chain transaction submitted by a node.
+    ///
+    /// Re-recording the same transaction ID replaces the earlier entry.
+    pub fn record_transaction(&self, tx: &WalletTransaction) -> Result<()> {
+        let tx = tx.clone();
+        self.db.run(move |client| {
+            client.execute(
+                "INSERT INTO wallet_transactions (transaction_id, kind, amount, timestamp)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (transaction_id) DO UPDATE SET
+                     kind = excluded.kind, amount = excluded.amount,
+                     timestamp = excluded.timestamp",
+                &[
+                    &tx.transaction_id,
+                    &tx.kind.as_str(),
+                    &(tx.amount as i64),
+                    &(tx.timestamp as i64),
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Get the most recent on-chain transactions, newest first.
+    pub fn recent_transactions(&self, limit: u32) -> Result<Vec<WalletTransaction>> {
+        self.db.run(move |client| {
+            let rows = client.query(
+                "SELECT transaction_id, kind, amount, timestamp FROM wallet_transactions
+                 ORDER BY timestamp DESC LIMIT $1",
+                &[&(limit as i64)],
+            )?;
+            Ok(rows
+                .iter()
+                .filter_map(|row| {
+                    WalletTransactionKind::parse(row.get(1)).map(|kind| WalletTransaction {
+                        transaction_id: row.get(0),
+                        kind,
+                        amount: row.get::<_, i64>(2) as Amount,
+                        timestamp: row.get::<_, i64>(3) as Timestamp,
+                    })
+                })
+                .collect())
+        })
+    }
+
+    /// Get the most recent wallet transaction of a kind.
+    pub fn latest_transaction(
+        &self,
+        kind: WalletTransactionKind,
+    ) -> Result<Option<WalletTransaction>> {
+        self.db.run(move |client| {
+            let row = client.query_opt(
+                "SELECT transaction_id, amount, timestamp FROM wallet_transactions
+                 WHERE kind = $1 ORDER BY timestamp DESC LIMIT 1",
+                &[&kind.as_str()],
+            )?;
+            Ok(row.map(|row| WalletTransaction {
+                transaction_id: row.get(0),
+                kind,
+                amount: row.get::<_, i64>(1) as Amount,
+                timestamp: row.get::<_, i64>(2) as Timestamp,
+            }))
+        })
+    }
+}
+
+impl SettlementQueueStore for PgSettlementQueue {
+    fn enqueue(&self, distribution: QueuedDistribution) -> Result<()> {
+        self.db.run(move |client| {
+            client.execute(
+                "INSERT INTO settlement_queue (payment_id, recipient, amount, source_hash, queued_at)
+                 VALUES ($1, $2, $3, $4, $5)",
+                &[
+                    &distribution.payment_id.0.as_slice(),
+                    &distribution.recipient.0.as_slice(),
+                    &(distribution.amount as i64),
+                    &distribution.source_hash.0.as_slice(),
+                    &(distribution.queued_at as i64),
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn get_pending(&self) -> Result<Vec<QueuedDistribution>> {
+        self.query_pending(None)
+    }
+
+    fn get_pending_for(&self, recipient: &PeerId) -> Result<Vec<QueuedDistribution>> {
+        self.query_pending(Some(*recipient))
+    }
+
+    fn get_pending_total(&self) -> Result<Amount> {
+        self.db.run(|client| {
+            let row = client.query_one(
+                "SELECT COALESCE(SUM(amount), 0)::BIGINT FROM settlement_queue WHERE NOT settled",
+                &[],
+            )?;
+            Ok(row.get::<_, i64>(0) as Amount)
+        })
+    }
+
+    fn mark_settled(&self, payment_ids: &[Hash], batch_id: &Hash) -> Result<()> {
+        let ids: Vec<Vec<u8>> = payment_ids.iter().map(|id| id.0.to_vec()).collect();
+        let batch_id = *batch_id;
+        self.db.run(move |client| {
+            client.execute(
+                "UPDATE settlement_queue SET settled = TRUE, batch_id = $2
+                 WHERE payment_id = ANY($1)",
+                &[&ids, &batch_id.0.as_slice()],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn get_last_settlement_time(&self) -> Result<Option<Timestamp>> {
+        self.db.run(|client| {
+            let row = client.query_opt(
+                "SELECT value FROM settlement_meta WHERE key = 'last_settlement_time'",
+                &[],
+            )?;
+            row.map(|row| {
+                row.get::<_, &str>(0)
+                    .parse()
+                    .map_err(|_| StoreError::invalid_data("invalid last settlement time"))
+            })
+            .transpose()
+        })
+    }
+
+    fn set_last_settlement_time(&self, timestamp: Timestamp) -> Result<()> {
+        self.db.run(move |client| {
+            client.execute(
+                "INSERT INTO settlement_meta (key, value) VALUES ('last_settlement_time', $1)
+                 ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+                &[&timestamp.to_string()],
+            )?;
+            Ok(())
+        })
+    }
+}
+
+/// Convert bytes to Hash.
+fn bytes_to_hash(bytes: &[u8]) -> Hash {
+    let mut arr = [0u8; 32];
+    if bytes.len() == 32 {
+        arr.copy_from_slice(bytes);
+    }
+    Hash(arr)
+}
+
+/// Convert bytes to PeerId.
+fn bytes_to_peer_id(bytes: &[u8]) -> PeerId {
+    let mut arr = [0u8; 20];
+    if bytes.len() == 20 {
+        arr.copy_from_slice(bytes);
+    }
+    PeerId(arr)
+}
+
+#[cfg(test)]
+mod tests {
+    //! These tests need a Postgres server; set `NODALYNC_TEST_DATABASE_URL`
+    //! to a scratch database to run them.
+
+    use super::*;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_types::{Metadata, Visibility};
+
+    fn test_db() -> Option<PgDatabase> {
+        let url = std::env::var("NODALYNC_TEST_DATABASE_URL").ok()?;
+        Some(PgDatabase::connect(&url).unwrap())
+    }
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    #[test]
+    fn test_manifest_roundtrip_and_filters() {
+        let Some(db) = test_db() else { return };
+        let store = PgManifestStore::new(db);
+
+        let hash = content_hash(format!("{:?}", std::time::SystemTime::now()).as_bytes());
+        let metadata =
+            Metadata::new("Shared Manifest", 42).with_tags(vec!["Science/Biology".into()]);
+        let mut manifest = Manifest::new_l0(hash, test_peer_id(), metadata, 1_000);
+        manifest.visibility = Visibility::Shared;
+        store.store(&manifest).unwrap();
+        store.store(&manifest).unwrap();
+
+        assert_eq!(store.load(&hash).unwrap(), Some(manifest.clone()));
+
+        let filter = ManifestFilter::new()
+            .with_tag("science")
+            .with_owner(manifest.owner);
+        let listed = store.list(filter).unwrap();
+        assert_eq!(listed.len(), 1);
+
+        manifest.economics.price = 7;
+        manifest.created_at = 9_999;
+        store.update(&manifest).unwrap();
+        let loaded = store.load(&hash).unwrap().unwrap();
+        assert_eq!(loaded.economics.price, 7);
+        assert_eq!(loaded.created_at, 1_000);
+
+        store.delete(&hash).unwrap();
+        assert!(store.load(&hash).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_settlement_lock_is_exclusive() {
+        let (Some(first), Some(second)) = (test_db(), test_db()) else {
+            return;
+        };
+
+        let lock = PgSettlementQueue::new(first).try_lock_batch().unwrap();
+        assert!(lock.is_some());
+        let queue = PgSettlementQueue::new(second);
+        assert!(queue.try_lock_batch().unwrap().is_none());
+
+        drop(lock);
+        // The release is sent in the background; a query on the same
+        // connection runs after it
+        let _ = queue.pending_count();
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert!(queue.try_lock_batch().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_payment_nonce_is_shared() {
+        let (Some(first), Some(second)) = (test_db(), test_db()) else {
+            return;
+        };
+        let channel_id = content_hash(format!("{:?}", std::time::SystemTime::now()).as_bytes());
+        let peer = test_peer_id();
+
+        let a = PgChannelStore::new(first);
+        let b = PgChannelStore::new(second);
+        a.record_payment_nonce(&channel_id, &peer, PaymentDirection::Received, 5, 1)
+            .unwrap();
+        assert!(matches!(
+            b.record_payment_nonce(&channel_id, &peer, PaymentDirection::Received, 5, 2),
+            Err(StoreError::StaleNonce { nonce: 5, last: 5 })
+        ));
+        b.record_payment_nonce(&channel_id, &peer, PaymentDirection::Received, 6, 2)
+            .unwrap();
+        assert_eq!(
+            a.payment_nonces(&channel_id)
+                .unwrap()
+                .unwrap()
+                .last_received,
+            6
+        );
+
+        a.delete(&peer).unwrap();
+    }
+}
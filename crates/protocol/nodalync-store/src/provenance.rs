@@ -31,7 +31,7 @@ impl SqliteProvenanceGraph {
 }
 
 impl ProvenanceGraph for SqliteProvenanceGraph {
-    fn add(&mut self, hash: &Hash, derived_from: &[Hash]) -> Result<()> {
+    fn add(&self, hash: &Hash, derived_from: &[Hash]) -> Result<()> {
         let conn = self
             .conn
             .lock()
@@ -193,7 +193,7 @@ impl ProvenanceGraph for SqliteProvenanceGraph {
         Ok(false)
     }
 
-    fn cache_root(&mut self, content_hash: &Hash, entry: &ProvenanceEntry) -> Result<()> {
+    fn cache_root(&self, content_hash: &Hash, entry: &ProvenanceEntry) -> Result<()> {
         let conn = self
             .conn
             .lock()
@@ -243,7 +243,7 @@ impl SqliteProvenanceGraph {
     }
 
     /// Clear the root cache for a content hash.
-    pub fn clear_cache(&mut self, hash: &Hash) -> Result<()> {
+    pub fn clear_cache(&self, hash: &Hash) -> Result<()> {
         let conn = self
             .conn
             .lock()
@@ -336,7 +336,7 @@ mod tests {
 
     #[test]
     fn test_add_and_get_derivations() {
-        let mut graph = setup_graph();
+        let graph = setup_graph();
 
         let source1 = content_hash(b"source1");
         let source2 = content_hash(b"source2");
@@ -361,7 +361,7 @@ mod tests {
 
     #[test]
     fn test_is_ancestor() {
-        let mut graph = setup_graph();
+        let graph = setup_graph();
 
         let root = content_hash(b"root");
         let middle = content_hash(b"middle");
@@ -385,7 +385,7 @@ mod tests {
 
     #[test]
     fn test_get_roots_l0() {
-        let mut graph = setup_graph();
+        let graph = setup_graph();
 
         let l0 = content_hash(b"l0 content");
         graph.add(&l0, &[]).unwrap();
@@ -397,7 +397,7 @@ mod tests {
 
     #[test]
     fn test_get_roots_derived() {
-        let mut graph = setup_graph();
+        let graph = setup_graph();
 
         let l0_1 = content_hash(b"l0_1");
         let l0_2 = content_hash(b"l0_2");
@@ -417,7 +417,7 @@ mod tests {
 
     #[test]
     fn test_cache_root() {
-        let mut graph = setup_graph();
+        let graph = setup_graph();
 
         let content = content_hash(b"content");
         let (_, public_key) = generate_identity();
@@ -437,7 +437,7 @@ mod tests {
 
     #[test]
     fn test_cache_root_weight_accumulation() {
-        let mut graph = setup_graph();
+        let graph = setup_graph();
 
         let content = content_hash(b"content");
         let (_, public_key) = generate_identity();
@@ -457,7 +457,7 @@ mod tests {
 
     #[test]
     fn test_clear_cache() {
-        let mut graph = setup_graph();
+        let graph = setup_graph();
 
         let content = content_hash(b"content");
         let (_, public_key) = generate_identity();
@@ -475,7 +475,7 @@ mod tests {
 
     #[test]
     fn test_diamond_provenance() {
-        let mut graph = setup_graph();
+        let graph = setup_graph();
 
         //     root
         //    /    \
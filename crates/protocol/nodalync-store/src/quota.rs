@@ -0,0 +1,245 @@
+//! Daily quota usage storage.
+//!
+//! Counts the queries and bytes served to each peer in the current quota
+//! period (UTC day), per content hash and node-wide, so the daily quotas
+//! set on content or the node can be enforced. Only the current period is
+//! kept for each peer.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{Hash, PeerId};
+
+use crate::error::{Result, StoreError};
+use crate::traits::QuotaStore;
+use crate::types::QuotaUsage;
+
+/// Content hash column value of a peer's node-wide usage.
+const NODE_WIDE: &[u8] = &[];
+
+/// SQLite-based quota usage store.
+pub struct SqliteQuotaStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteQuotaStore {
+    /// Create a new quota usage store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+}
+
+fn scope(content_hash: Option<&Hash>) -> Vec<u8> {
+    content_hash.map_or_else(|| NODE_WIDE.to_vec(), |hash| hash.0.to_vec())
+}
+
+impl QuotaStore for SqliteQuotaStore {
+    fn usage(&self, peer: &PeerId, content_hash: Option<&Hash>, period: u64) -> Result<QuotaUsage> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let usage = conn
+            .prepare_cached(
+                "SELECT queries, bytes FROM quota_usage
+                 WHERE peer_id = ?1 AND content_hash = ?2 AND period = ?3",
+            )?
+            .query_row(
+                params![peer.0.to_vec(), scope(content_hash), period as i64],
+                |row| {
+                    Ok(QuotaUsage {
+                        queries: row.get::<_, i64>(0)? as u32,
+                        bytes: row.get::<_, i64>(1)? as u64,
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(usage.unwrap_or_default())
+    }
+
+    fn record_use(
+        &self,
+        peer: &PeerId,
+        content_hash: &Hash,
+        period: u64,
+        bytes: u64,
+    ) -> Result<()> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let tx = conn.transaction()?;
+        tx.execute(
+            "DELETE FROM quota_usage WHERE peer_id = ?1 AND period < ?2",
+            params![peer.0.to_vec(), period as i64],
+        )?;
+        for scope in [content_hash.0.as_slice(), NODE_WIDE] {
+            tx.execute(
+                "INSERT INTO quota_usage (peer_id, content_hash, period, queries, bytes)
+                 VALUES (?1, ?2, ?3, 1, ?4)
+                 ON CONFLICT(peer_id, content_hash, period)
+                 DO UPDATE SET queries = queries + 1, bytes = bytes + excluded.bytes",
+                params![peer.0.to_vec(), scope, period as i64, bytes as i64],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn release_use(
+        &self,
+        peer: &PeerId,
+        content_hash: &Hash,
+        period: u64,
+        bytes: u64,
+    ) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        conn.execute(
+            "UPDATE quota_usage
+             SET queries = MAX(queries - 1, 0), bytes = MAX(bytes - ?4, 0)
+             WHERE peer_id = ?1 AND content_hash IN (?2, ?3) AND period = ?5",
+            params![
+                peer.0.to_vec(),
+                content_hash.0.to_vec(),
+                NODE_WIDE,
+                bytes as i64,
+                period as i64
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn peers(&self, content_hash: Option<&Hash>, period: u64) -> Result<Vec<(PeerId, QuotaUsage)>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare_cached(
+            "SELECT peer_id, queries, bytes FROM quota_usage
+             WHERE content_hash = ?1 AND period = ?2
+             ORDER BY queries DESC, bytes DESC",
+        )?;
+        let rows = stmt.query_map(params![scope(content_hash), period as i64], |row| {
+            let peer: Vec<u8> = row.get(0)?;
+            Ok((
+                bytes_to_peer_id(&peer),
+                QuotaUsage {
+                    queries: row.get::<_, i64>(1)? as u32,
+                    bytes: row.get::<_, i64>(2)? as u64,
+                },
+            ))
+        })?;
+
+        Ok(rows.collect::<std::result::Result<_, _>>()?)
+    }
+
+    fn reset(&self, peer: &PeerId) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let removed = conn.execute(
+            "DELETE FROM quota_usage WHERE peer_id = ?1",
+            [peer.0.to_vec()],
+        )?;
+
+        Ok(removed > 0)
+    }
+}
+
+/// Convert bytes to PeerId.
+fn bytes_to_peer_id(bytes: &[u8]) -> PeerId {
+    let mut arr = [0u8; 20];
+    if bytes.len() >= 20 {
+        arr.copy_from_slice(&bytes[..20]);
+    }
+    PeerId::from_bytes(arr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+
+    fn setup_store() -> SqliteQuotaStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteQuotaStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    #[test]
+    fn test_record_and_reset() {
+        let store = setup_store();
+        let hash = content_hash(b"limited content");
+        let other = content_hash(b"other content");
+        let alice = test_peer_id();
+        let bob = test_peer_id();
+
+        assert_eq!(store.usage(&alice, None, 7).unwrap(), QuotaUsage::default());
+        store.record_use(&alice, &hash, 7, 100).unwrap();
+        store.record_use(&alice, &hash, 7, 50).unwrap();
+        store.record_use(&alice, &other, 7, 10).unwrap();
+        store.record_use(&bob, &hash, 7, 1).unwrap();
+
+        // Usage is counted per content and node-wide
+        assert_eq!(
+            store.usage(&alice, Some(&hash), 7).unwrap(),
+            QuotaUsage {
+                queries: 2,
+                bytes: 150
+            }
+        );
+        assert_eq!(
+            store.usage(&alice, None, 7).unwrap(),
+            QuotaUsage {
+                queries: 3,
+                bytes: 160
+            }
+        );
+        let peers = store.peers(Some(&hash), 7).unwrap();
+        assert_eq!(peers.len(), 2);
+        assert_eq!(peers[0].0, alice);
+        assert_eq!(store.peers(None, 8).unwrap(), Vec::new());
+
+        // Releasing takes a query back from the content and node-wide
+        store.release_use(&alice, &hash, 7, 50).unwrap();
+        assert_eq!(
+            store.usage(&alice, None, 7).unwrap(),
+            QuotaUsage {
+                queries: 2,
+                bytes: 110
+            }
+        );
+        assert_eq!(store.usage(&alice, Some(&hash), 7).unwrap().queries, 1);
+
+        // A new period starts from nothing and drops the old one
+        store.record_use(&alice, &hash, 8, 5).unwrap();
+        assert_eq!(store.usage(&alice, None, 8).unwrap().queries, 1);
+        assert_eq!(store.usage(&alice, None, 7).unwrap(), QuotaUsage::default());
+        assert_eq!(store.usage(&bob, None, 7).unwrap().queries, 1);
+
+        assert!(store.reset(&bob).unwrap());
+        assert!(!store.reset(&bob).unwrap());
+        assert_eq!(
+            store.usage(&bob, Some(&hash), 7).unwrap(),
+            QuotaUsage::default()
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Replica delegation storage.
+//!
+//! A replica holds one delegation per primary whose catalog it serves,
+//! the newest one imported. Delegations are kept after they expire, so
+//! the primary can be told apart from other publishers until the entry is
+//! removed.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::PeerId;
+use nodalync_types::ReplicaDelegation;
+
+use crate::error::{Result, StoreError};
+use crate::traits::ReplicaStore;
+
+/// SQLite-based replica delegation store.
+pub struct SqliteReplicaStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteReplicaStore {
+    /// Create a new replica store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+}
+
+impl ReplicaStore for SqliteReplicaStore {
+    fn set_delegation(&self, delegation: &ReplicaDelegation) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let data = serde_json::to_string(delegation)?;
+        conn.execute(
+            "INSERT OR REPLACE INTO replica_delegations (primary_peer, data, expires_at)
+             VALUES (?1, ?2, ?3)",
+            params![
+                delegation.primary.0.to_vec(),
+                data,
+                delegation.expires_at as i64
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_delegation(&self, primary: &PeerId) -> Result<Option<ReplicaDelegation>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let data: Option<String> = conn
+            .prepare_cached("SELECT data FROM replica_delegations WHERE primary_peer = ?1")?
+            .query_row([primary.0.to_vec()], |row| row.get(0))
+            .optional()?;
+
+        match data {
+            Some(data) => Ok(Some(serde_json::from_str(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn list_delegations(&self) -> Result<Vec<ReplicaDelegation>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt =
+            conn.prepare("SELECT data FROM replica_delegations ORDER BY expires_at ASC")?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        rows.iter()
+            .map(|data| Ok(serde_json::from_str(data)?))
+            .collect()
+    }
+
+    fn remove_delegation(&self, primary: &PeerId) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let removed = conn.execute(
+            "DELETE FROM replica_delegations WHERE primary_peer = ?1",
+            [primary.0.to_vec()],
+        )?;
+
+        Ok(removed > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key, Timestamp};
+
+    fn setup_store() -> SqliteReplicaStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteReplicaStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    fn test_delegation(expires_at: Timestamp) -> ReplicaDelegation {
+        let (_, public_key) = generate_identity();
+        ReplicaDelegation::new(
+            peer_id_from_public_key(&public_key),
+            public_key,
+            PeerId::from_bytes([2u8; 20]),
+            None,
+            1_000,
+            expires_at,
+        )
+    }
+
+    #[test]
+    fn test_set_and_get_delegation() {
+        let store = setup_store();
+        let delegation = test_delegation(2_000);
+        assert_eq!(store.get_delegation(&delegation.primary).unwrap(), None);
+
+        store.set_delegation(&delegation).unwrap();
+        assert_eq!(
+            store.get_delegation(&delegation.primary).unwrap(),
+            Some(delegation.clone())
+        );
+
+        // A renewed delegation replaces the old one
+        let mut renewed = delegation.clone();
+        renewed.expires_at = 3_000;
+        store.set_delegation(&renewed).unwrap();
+        assert_eq!(
+            store.get_delegation(&delegation.primary).unwrap(),
+            Some(renewed)
+        );
+        assert_eq!(store.list_delegations().unwrap().len(), 1);
+
+        assert!(store.remove_delegation(&delegation.primary).unwrap());
+        assert!(!store.remove_delegation(&delegation.primary).unwrap());
+        assert_eq!(store.get_delegation(&delegation.primary).unwrap(), None);
+    }
+
+    #[test]
+    fn test_list_delegations_soonest_first() {
+        let store = setup_store();
+        let later = test_delegation(3_000);
+        let sooner = test_delegation(2_000);
+        store.set_delegation(&later).unwrap();
+        store.set_delegation(&sooner).unwrap();
+        assert_eq!(store.list_delegations().unwrap(), vec![sooner, later]);
+    }
+}
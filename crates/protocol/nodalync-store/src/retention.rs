@@ -0,0 +1,378 @@
+//! Data retention.
+//!
+//! Record counts and purges by age for each [`RetentionCategory`]. Each
+//! category is kept in its own tables; this module knows which timestamp
+//! ages each of them and which records must be kept regardless (peers we
+//! have a channel with, and channels that aren't closed). Cached content
+//! also has files on disk, so purging it goes through the cache store; see
+//! [`NodeState::purge_expired`](crate::NodeState::purge_expired).
+
+use rusqlite::Connection;
+
+use nodalync_crypto::{Hash, Timestamp};
+use nodalync_types::ChannelState;
+
+use crate::error::Result;
+use crate::types::{RetentionCategory, RetentionStats};
+
+/// Stored state of a closed channel.
+const CLOSED: u8 = ChannelState::Closed as u8;
+
+/// Peers with a channel that isn't closed are never expired.
+fn peer_purgeable() -> String {
+    format!("peer_id NOT IN (SELECT peer_id FROM channels WHERE state != {CLOSED})")
+}
+
+/// Count the records in a category, and those last touched before
+/// `older_than` (ms).
+pub(crate) fn retention_stats(
+    conn: &Connection,
+    category: RetentionCategory,
+    older_than: Timestamp,
+) -> Result<RetentionStats> {
+    match category {
+        // received_at is in seconds
+        RetentionCategory::Announcements => count(
+            conn,
+            "announcements",
+            "received_at * 1000",
+            "1",
+            "1",
+            older_than,
+        ),
+        RetentionCategory::CachedContent => {
+            count(conn, "cache", "queried_at", "1", "1", older_than)
+        }
+        RetentionCategory::PeerRecords => count(
+            conn,
+            "peers",
+            "last_seen",
+            "1",
+            &peer_purgeable(),
+            older_than,
+        ),
+        RetentionCategory::ChannelHistory => Ok(merge(
+            count(
+                conn,
+                "channels",
+                "last_update",
+                &format!("state = {CLOSED}"),
+                "1",
+                older_than,
+            )?,
+            count(
+                conn,
+                "payments",
+                "timestamp",
+                "settled = 1",
+                "1",
+                older_than,
+            )?,
+        )),
+        RetentionCategory::Analytics => Ok(merge(
+            merge(
+                count(conn, "content_access", "timestamp", "1", "1", older_than)?,
+                count(conn, "usage_reports", "timestamp", "1", "1", older_than)?,
+            ),
+            merge(
+                count(conn, "popularity", "updated_at", "1", "1", older_than)?,
+                count(
+                    conn,
+                    "settlement_failures",
+                    "timestamp",
+                    "1",
+                    "1",
+                    older_than,
+                )?,
+            ),
+        )),
+    }
+}
+
+/// Hashes of cached content queried before `older_than` (ms).
+pub(crate) fn expired_cache_entries(conn: &Connection, older_than: Timestamp) -> Result<Vec<Hash>> {
+    let mut stmt = conn.prepare("SELECT hash FROM cache WHERE queried_at < ?1")?;
+    let hashes = stmt
+        .query_map([older_than as i64], |row| row.get::<_, Vec<u8>>(0))?
+        .filter_map(|r| r.ok())
+        .filter_map(|bytes| bytes.try_into().ok().map(Hash))
+        .collect();
+    Ok(hashes)
+}
+
+/// Delete the records in a category last touched before `older_than` (ms).
+///
+/// Returns the number of records deleted, counted as in
+/// [`retention_stats`]. Cached content isn't handled here, since its files
+/// live outside the database.
+pub(crate) fn purge_records(
+    conn: &mut Connection,
+    category: RetentionCategory,
+    older_than: Timestamp,
+) -> Result<u64> {
+    let older_than = older_than as i64;
+
+    let deleted = match category {
+        RetentionCategory::Announcements => conn.execute(
+            "DELETE FROM announcements WHERE received_at * 1000 < ?1",
+            [older_than],
+        )?,
+        RetentionCategory::CachedContent => 0,
+        RetentionCategory::PeerRecords => conn.execute(
+            &format!(
+                "DELETE FROM peers WHERE last_seen < ?1 AND {}",
+                peer_purgeable()
+            ),
+            [older_than],
+        )?,
+        RetentionCategory::ChannelHistory => {
+            let tx = conn.transaction()?;
+            let payments = tx.execute(
+                "DELETE FROM payments WHERE settled = 1 AND timestamp < ?1",
+                [older_than],
+            )?;
+            // Everything left of an expired closed channel goes with it
+            for table in ["payments", "channel_checkpoints"] {
+                tx.execute(
+                    &format!(
+                        "DELETE FROM {table} WHERE channel_id IN
+                         (SELECT channel_id FROM channels WHERE state = {CLOSED} AND last_update < ?1)"
+                    ),
+                    [older_than],
+                )?;
+            }
+            let channels = tx.execute(
+                &format!("DELETE FROM channels WHERE state = {CLOSED} AND last_update < ?1"),
+                [older_than],
+            )?;
+            tx.commit()?;
+            payments + channels
+        }
+        RetentionCategory::Analytics => {
+            conn.execute(
+                "DELETE FROM content_access WHERE timestamp < ?1",
+                [older_than],
+            )? + conn.execute(
+                "DELETE FROM usage_reports WHERE timestamp < ?1",
+                [older_than],
+            )? + conn.execute("DELETE FROM popularity WHERE updated_at < ?1", [older_than])?
+                + conn.execute(
+                    "DELETE FROM settlement_failures WHERE timestamp < ?1",
+                    [older_than],
+                )?
+        }
+    };
+
+    Ok(deleted as u64)
+}
+
+/// Count the rows of `table` in `scope`, with the oldest `age`, and those
+/// older than the cutoff that `purgeable` allows deleting.
+///
+/// `?1` is the cutoff in the clauses.
+fn count(
+    conn: &Connection,
+    table: &str,
+    age: &str,
+    scope: &str,
+    purgeable: &str,
+    older_than: Timestamp,
+) -> Result<RetentionStats> {
+    let sql = format!(
+        "SELECT COUNT(*), MIN({age}), COALESCE(SUM({age} < ?1 AND {purgeable}), 0)
+         FROM {table} WHERE {scope}"
+    );
+    let (records, oldest, expired): (i64, Option<i64>, i64) =
+        conn.query_row(&sql, [older_than as i64], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?;
+
+    Ok(RetentionStats {
+        records: records as u64,
+        expired: expired as u64,
+        oldest: oldest.map(|t| t as Timestamp),
+    })
+}
+
+fn merge(a: RetentionStats, b: RetentionStats) -> RetentionStats {
+    RetentionStats {
+        records: a.records + b.records,
+        expired: a.expired + b.expired,
+        oldest: match (a.oldest, b.oldest) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::traits::{AccessLogStore, CacheStore, ChannelStore, PeerStore};
+    use crate::types::{AccessKind, AccessRecord, AccessRequester, CachedContent, PeerInfo};
+    use crate::{NodeState, RetentionCategory};
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key, Signature};
+    use nodalync_types::{Channel, ChannelState, Payment, PeerId};
+    use nodalync_wire::payload::PaymentReceipt;
+
+    fn add_peer(state: &mut NodeState, last_seen: u64) -> PeerId {
+        let (_, public_key) = generate_identity();
+        let peer = peer_id_from_public_key(&public_key);
+        state
+            .peers
+            .upsert(&PeerInfo::new(peer, public_key, vec![], last_seen))
+            .unwrap();
+        peer
+    }
+
+    fn add_channel(state: &mut NodeState, peer: PeerId, closed: bool, last_update: u64) -> Channel {
+        let mut channel = Channel::new(content_hash(&peer.0), peer, 100, last_update);
+        if closed {
+            channel.state = ChannelState::Closed;
+        }
+        state.channels.create(&peer, channel.clone()).unwrap();
+        channel
+    }
+
+    fn payment(channel: &Channel, id: &[u8], timestamp: u64) -> Payment {
+        Payment {
+            id: content_hash(id),
+            channel_id: channel.channel_id,
+            amount: 10,
+            recipient: channel.peer_id,
+            query_hash: content_hash(b"content"),
+            provenance: vec![],
+            timestamp,
+            signature: Signature::from_bytes([0u8; 64]),
+        }
+    }
+
+    #[test]
+    fn test_retention_stats_and_purge() {
+        let mut state = NodeState::open_in_memory().unwrap();
+
+        // Peers: two stale, one of them with an open channel, and one fresh
+        let stale = add_peer(&mut state, 1000);
+        let with_channel = add_peer(&mut state, 1000);
+        add_peer(&mut state, 5000);
+        let open = add_channel(&mut state, with_channel, false, 1000);
+
+        // Channel history: a closed channel with a pending payment, and a
+        // settled payment in the open channel
+        let closed = add_channel(&mut state, stale, true, 2000);
+        state
+            .channels
+            .add_payment(&stale, payment(&closed, b"pending", 2000))
+            .unwrap();
+        let settled = payment(&open, b"settled", 1500);
+        state
+            .channels
+            .add_payment(&with_channel, settled.clone())
+            .unwrap();
+        state
+            .channels
+            .clear_payments(&with_channel, &[settled.id])
+            .unwrap();
+
+        // Analytics and cached content, one old and one new of each
+        for (i, timestamp) in [1000u64, 5000].into_iter().enumerate() {
+            state
+                .access_log
+                .record(&AccessRecord::new(
+                    content_hash(b"content"),
+                    AccessRequester::Peer(stale),
+                    AccessKind::Query,
+                    10,
+                    timestamp,
+                ))
+                .unwrap();
+            state
+                .cache
+                .cache(CachedContent::new(
+                    content_hash(&[i as u8]),
+                    vec![i as u8],
+                    stale,
+                    timestamp,
+                    PaymentReceipt {
+                        payment_id: content_hash(b"payment"),
+                        amount: 10,
+                        timestamp,
+                        channel_nonce: 1,
+                        distributor_signature: Signature::from_bytes([0u8; 64]),
+                        app_fee: 0,
+                        app_fee_recipient: None,
+                        referral_fee: 0,
+                        referrer: None,
+                    },
+                ))
+                .unwrap();
+        }
+
+        let cutoff = 3000;
+        let stats = |state: &NodeState, category| state.retention_stats(category, cutoff).unwrap();
+
+        let peers = stats(&state, RetentionCategory::PeerRecords);
+        assert_eq!((peers.records, peers.expired), (3, 1));
+        assert_eq!(peers.oldest, Some(1000));
+        let history = stats(&state, RetentionCategory::ChannelHistory);
+        assert_eq!((history.records, history.expired), (2, 2));
+        assert_eq!(history.oldest, Some(1500));
+        let analytics = stats(&state, RetentionCategory::Analytics);
+        assert_eq!((analytics.records, analytics.expired), (2, 1));
+        let cache = stats(&state, RetentionCategory::CachedContent);
+        assert_eq!((cache.records, cache.expired), (2, 1));
+
+        for (category, deleted) in [
+            (RetentionCategory::PeerRecords, 1),
+            (RetentionCategory::ChannelHistory, 2),
+            (RetentionCategory::Analytics, 1),
+            (RetentionCategory::CachedContent, 1),
+        ] {
+            assert_eq!(state.purge_expired(category, cutoff).unwrap(), deleted);
+            let after = stats(&state, category);
+            assert_eq!(after.expired, 0);
+        }
+
+        // The peer with an open channel and the open channel are kept
+        assert!(state.peers.get(&with_channel).unwrap().is_some());
+        assert!(state.peers.get(&stale).unwrap().is_none());
+        assert!(state.channels.get(&with_channel).unwrap().is_some());
+        assert!(state.channels.get(&stale).unwrap().is_none());
+        assert!(state.cache.is_cached(&content_hash(&[1u8])));
+        assert!(!state.cache.is_cached(&content_hash(&[0u8])));
+    }
+
+    #[test]
+    fn test_announcement_retention() {
+        use nodalync_types::{ContentType, L1Summary};
+        use nodalync_wire::AnnouncePayload;
+
+        let state = NodeState::open_in_memory().unwrap();
+        let hash = content_hash(b"announced");
+        state.store_announcement(AnnouncePayload {
+            hash,
+            content_type: ContentType::L0,
+            title: "Announced".to_string(),
+            l1_summary: L1Summary::empty(hash),
+            price: 0,
+            addresses: vec![],
+            publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
+        });
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let category = RetentionCategory::Announcements;
+        let stats = state.retention_stats(category, now - 60_000).unwrap();
+        assert_eq!((stats.records, stats.expired), (1, 0));
+        assert_eq!(state.purge_expired(category, now - 60_000).unwrap(), 0);
+
+        let later = now + 60_000;
+        assert_eq!(state.retention_stats(category, later).unwrap().expired, 1);
+        assert_eq!(state.purge_expired(category, later).unwrap(), 1);
+        assert_eq!(state.announcement_count(), 0);
+    }
+}
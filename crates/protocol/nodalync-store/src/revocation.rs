@@ -0,0 +1,134 @@
+//! Key revocation storage.
+//!
+//! Revocation certificates mark identity keys as compromised, whether we
+//! received them from the network or broadcast them ourselves. A revoked
+//! key stays revoked, so certificates are never removed.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{PeerId, Timestamp};
+use nodalync_types::RevocationCertificate;
+
+use crate::error::{Result, StoreError};
+use crate::traits::RevocationStore;
+
+/// SQLite-based revocation store.
+pub struct SqliteRevocationStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteRevocationStore {
+    /// Create a new revocation store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+}
+
+impl RevocationStore for SqliteRevocationStore {
+    fn store(&self, certificate: &RevocationCertificate, received_at: Timestamp) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let data = serde_json::to_string(certificate)?;
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO revocations (peer_id, data, received_at)
+             VALUES (?1, ?2, ?3)",
+            params![certificate.peer_id.0.to_vec(), data, received_at as i64],
+        )?;
+
+        Ok(inserted > 0)
+    }
+
+    fn get(&self, peer_id: &PeerId) -> Result<Option<RevocationCertificate>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let data: Option<String> = conn
+            .prepare_cached("SELECT data FROM revocations WHERE peer_id = ?1")?
+            .query_row([peer_id.0.to_vec()], |row| row.get(0))
+            .optional()?;
+
+        Ok(data.map(|data| serde_json::from_str(&data)).transpose()?)
+    }
+
+    fn is_revoked(&self, peer_id: &PeerId) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let count: i64 = conn
+            .prepare_cached("SELECT COUNT(*) FROM revocations WHERE peer_id = ?1")?
+            .query_row([peer_id.0.to_vec()], |row| row.get(0))?;
+
+        Ok(count > 0)
+    }
+
+    fn list(&self) -> Result<Vec<RevocationCertificate>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare("SELECT data FROM revocations ORDER BY received_at DESC")?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        rows.iter()
+            .map(|data| Ok(serde_json::from_str(data)?))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+
+    fn setup_store() -> SqliteRevocationStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteRevocationStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    fn test_certificate(created_at: Timestamp) -> RevocationCertificate {
+        let (_, public_key) = generate_identity();
+        RevocationCertificate::new(peer_id_from_public_key(&public_key), public_key, created_at)
+    }
+
+    #[test]
+    fn test_store_and_get() {
+        let store = setup_store();
+        let certificate = test_certificate(100);
+        let peer_id = certificate.peer_id;
+
+        assert!(!store.is_revoked(&peer_id).unwrap());
+        assert!(store.store(&certificate, 100).unwrap());
+        assert!(store.is_revoked(&peer_id).unwrap());
+        assert_eq!(store.get(&peer_id).unwrap(), Some(certificate.clone()));
+
+        // The first certificate for a key is kept
+        let mut later = certificate.clone();
+        later.created_at = 200;
+        assert!(!store.store(&later, 200).unwrap());
+        assert_eq!(store.get(&peer_id).unwrap(), Some(certificate));
+    }
+
+    #[test]
+    fn test_list_newest_first() {
+        let store = setup_store();
+        let first = test_certificate(100);
+        let second = test_certificate(200);
+        store.store(&first, 100).unwrap();
+        store.store(&second, 200).unwrap();
+
+        assert_eq!(store.list().unwrap(), vec![second, first]);
+    }
+}
@@ -7,7 +7,7 @@ use rusqlite::Connection;
 use crate::error::Result;
 
 /// Schema version for migration tracking.
-pub const SCHEMA_VERSION: u32 = 3;
+pub const SCHEMA_VERSION: u32 = 42;
 
 /// Initialize the database schema.
 ///
@@ -79,384 +79,2410 @@ fn migrate_schema(conn: &Connection, from_version: u32) -> Result<()> {
         }
     }
 
+    // Migration from version 3 to 4: Add wallet transaction history
+    if from_version < 4 {
+        create_wallet_tables(conn)?;
+    }
+
+    // Migration from version 4 to 5: Add channel state checkpoints
+    if from_version < 5 {
+        create_channel_checkpoint_tables(conn)?;
+    }
+
+    // Migration from version 5 to 6: Add per-content access log
+    if from_version < 6 {
+        create_access_log_tables(conn)?;
+    }
+
+    // Migration from version 6 to 7: Add structured metadata and schema cache
+    if from_version < 7 {
+        for column in ["metadata_schema", "metadata_fields"] {
+            if let Err(e) = conn.execute(
+                &format!("ALTER TABLE manifests ADD COLUMN {} TEXT", column),
+                [],
+            ) {
+                if !e.to_string().contains("duplicate column") {
+                    tracing::warn!(error = %e, column, "Failed to add column to manifests");
+                }
+            }
+        }
+        create_metadata_schema_tables(conn)?;
+    }
+
+    // Migration from version 7 to 8: Add announcement publisher signatures
+    if from_version < 8 {
+        for column in ["publisher_key", "signature"] {
+            if let Err(e) = conn.execute(
+                &format!("ALTER TABLE announcements ADD COLUMN {} BLOB", column),
+                [],
+            ) {
+                if !e.to_string().contains("duplicate column") {
+                    tracing::warn!(error = %e, column, "Failed to add column to announcements");
+                }
+            }
+        }
+    }
+
+    // Migration from version 8 to 9: Add tag registry
+    if from_version < 9 {
+        create_tag_tables(conn)?;
+    }
+
+    // Migration from version 9 to 10: Add economic ledger
+    if from_version < 10 {
+        create_ledger_tables(conn)?;
+    }
+
+    // Migration from version 10 to 11: Add per-channel payment nonce tracking
+    if from_version < 11 {
+        create_payment_nonce_tables(conn)?;
+    }
+
+    // Migration from version 11 to 12: Add invoices
+    if from_version < 12 {
+        create_invoice_tables(conn)?;
+    }
+
+    // Migration from version 12 to 13: Add usage reports
+    if from_version < 13 {
+        create_usage_report_tables(conn)?;
+    }
+
+    // Migration from version 13 to 14: Add group membership lists
+    if from_version < 14 {
+        create_group_tables(conn)?;
+    }
+
+    // Migration from version 14 to 15: Add content encryption keys
+    if from_version < 15 {
+        create_content_key_tables(conn)?;
+    }
+
+    // Migration from version 15 to 16: Add tombstones
+    if from_version < 16 {
+        create_tombstone_tables(conn)?;
+    }
+
+    // Migration from version 16 to 17: Add content reports and moderation
+    if from_version < 17 {
+        create_moderation_tables(conn)?;
+    }
+
+    // Migration from version 17 to 18: Add manifest preview policies
+    if from_version < 18 {
+        if let Err(e) = conn.execute("ALTER TABLE manifests ADD COLUMN preview_policy TEXT", []) {
+            if !e.to_string().contains("duplicate column") {
+                tracing::warn!(error = %e, "Failed to add preview_policy column to manifests");
+            }
+        }
+    }
+
+    // Migration from version 18 to 19: Add key revocations
+    if from_version < 19 {
+        create_revocation_tables(conn)?;
+    }
+
+    // Migration from version 19 to 20: Add manifest publisher bonds
+    if from_version < 20 {
+        if let Err(e) = conn.execute("ALTER TABLE manifests ADD COLUMN bond TEXT", []) {
+            if !e.to_string().contains("duplicate column") {
+                tracing::warn!(error = %e, "Failed to add bond column to manifests");
+            }
+        }
+    }
+
+    // Migration from version 20 to 21: Add fraud proofs
+    if from_version < 21 {
+        create_fraud_proof_tables(conn)?;
+    }
+
+    // Migration from version 21 to 22: Add popularity counters
+    if from_version < 22 {
+        create_popularity_tables(conn)?;
+    }
+
+    // Migration from version 22 to 23: Add manifest chunk trees
+    if from_version < 23 {
+        if let Err(e) = conn.execute("ALTER TABLE manifests ADD COLUMN chunks TEXT", []) {
+            if !e.to_string().contains("duplicate column") {
+                tracing::warn!(error = %e, "Failed to add chunks column to manifests");
+            }
+        }
+    }
+
+    // Migration from version 23 to 24: Add advertised peer capabilities
+    if from_version < 24 {
+        if let Err(e) = conn.execute("ALTER TABLE peers ADD COLUMN capabilities INTEGER", []) {
+            if !e.to_string().contains("duplicate column") {
+                tracing::warn!(error = %e, "Failed to add capabilities column to peers");
+            }
+        }
+    }
+
+    // Migration from version 24 to 25: Add version fork flags and canonical pointers
+    if from_version < 25 {
+        if let Err(e) = conn.execute(
+            "ALTER TABLE manifests ADD COLUMN version_forked INTEGER NOT NULL DEFAULT 0",
+            [],
+        ) {
+            if !e.to_string().contains("duplicate column") {
+                tracing::warn!(error = %e, "Failed to add version_forked column to manifests");
+            }
+        }
+        create_canonical_version_tables(conn)?;
+    }
+
+    // Migration from version 25 to 26: Add cross-device sync tables
+    if from_version < 26 {
+        create_sync_tables(conn)?;
+    }
+
+    // Migration from version 26 to 27: Add replica delegation table
+    if from_version < 27 {
+        create_replica_tables(conn)?;
+    }
+
+    // Migration from version 27 to 28: Add webhook delivery table
+    if from_version < 28 {
+        create_webhook_tables(conn)?;
+    }
+
+    // Migration from version 28 to 29: Add activity digest tables and a
+    // timestamp index on the access log
+    if from_version < 29 {
+        create_access_log_tables(conn)?;
+        create_digest_tables(conn)?;
+    }
+
+    // Migration from version 29 to 30: Add manifest dynamic pricing rules
+    if from_version < 30 {
+        if let Err(e) = conn.execute("ALTER TABLE manifests ADD COLUMN pricing TEXT", []) {
+            if !e.to_string().contains("duplicate column") {
+                tracing::warn!(error = %e, "Failed to add pricing column to manifests");
+            }
+        }
+    }
+
+    // Migration from version 30 to 31: Add manifest priced sections
+    if from_version < 31 {
+        if let Err(e) = conn.execute("ALTER TABLE manifests ADD COLUMN sections TEXT", []) {
+            if !e.to_string().contains("duplicate column") {
+                tracing::warn!(error = %e, "Failed to add sections column to manifests");
+            }
+        }
+    }
+
+    // Migration from version 31 to 32: Add manifest metered unit hashes
+    if from_version < 32 {
+        if let Err(e) = conn.execute("ALTER TABLE manifests ADD COLUMN metering TEXT", []) {
+            if !e.to_string().contains("duplicate column") {
+                tracing::warn!(error = %e, "Failed to add metering column to manifests");
+            }
+        }
+    }
+
+    // Migration from version 32 to 33: Add free trial read counts
+    if from_version < 33 {
+        create_trial_tables(conn)?;
+    }
+
+    // Migration from version 33 to 34: Add manifest referral policies
+    if from_version < 34 {
+        if let Err(e) = conn.execute("ALTER TABLE manifests ADD COLUMN referral TEXT", []) {
+            if !e.to_string().contains("duplicate column") {
+                tracing::warn!(error = %e, "Failed to add referral column to manifests");
+            }
+        }
+    }
+
+    // Migration from version 34 to 35: Add standing orders
+    if from_version < 35 {
+        create_standing_order_tables(conn)?;
+    }
+
+    // Migration from version 35 to 36: Add peer latency statistics
+    if from_version < 36 {
+        if let Err(e) = conn.execute("ALTER TABLE peers ADD COLUMN latency TEXT", []) {
+            if !e.to_string().contains("duplicate column") {
+                tracing::warn!(error = %e, "Failed to add latency column to peers");
+            }
+        }
+    }
+
+    // Migration from version 36 to 37: Add content availability checks
+    if from_version < 37 {
+        create_availability_tables(conn)?;
+    }
+
+    // Migration from version 37 to 38: Add tenants
+    if from_version < 38 {
+        create_tenant_tables(conn)?;
+    }
+
+    // Migration from version 38 to 39: Add daily quota usage
+    if from_version < 39 {
+        create_quota_tables(conn)?;
+    }
+
+    // Migration from version 39 to 40: Add the purchase library
+    if from_version < 40 {
+        create_library_tables(conn)?;
+    }
+
+    // Migration from version 40 to 41: Add re-download entitlements
+    if from_version < 41 {
+        create_entitlement_tables(conn)?;
+    }
+
+    // Migration from version 41 to 42: Add channel dispute tracking
+    if from_version < 42 {
+        create_dispute_tables(conn)?;
+    }
+
     Ok(())
 }
 
-/// Create all database tables.
-fn create_tables(conn: &Connection) -> Result<()> {
-    // Manifests table
+/// Create the wallet transaction history table.
+fn create_wallet_tables(conn: &Connection) -> Result<()> {
     conn.execute(
-        "CREATE TABLE IF NOT EXISTS manifests (
-            hash BLOB PRIMARY KEY,
-            content_type INTEGER NOT NULL,
-            owner BLOB NOT NULL,
-            version_number INTEGER NOT NULL,
-            version_previous BLOB,
-            version_root BLOB NOT NULL,
-            version_timestamp INTEGER NOT NULL,
-            visibility INTEGER NOT NULL,
-            title TEXT NOT NULL,
-            description TEXT,
-            tags TEXT,
-            content_size INTEGER NOT NULL,
-            mime_type TEXT,
-            price INTEGER NOT NULL,
-            total_queries INTEGER NOT NULL DEFAULT 0,
-            total_revenue INTEGER NOT NULL DEFAULT 0,
-            access_control TEXT NOT NULL,
-            provenance TEXT NOT NULL,
-            created_at INTEGER NOT NULL,
-            updated_at INTEGER NOT NULL
+        "CREATE TABLE IF NOT EXISTS wallet_transactions (
+            transaction_id TEXT PRIMARY KEY,
+            kind TEXT NOT NULL,
+            amount INTEGER NOT NULL,
+            timestamp INTEGER NOT NULL
         )",
         [],
     )?;
 
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_manifests_visibility ON manifests(visibility)",
+        "CREATE INDEX IF NOT EXISTS idx_wallet_transactions_timestamp ON wallet_transactions(timestamp)",
         [],
     )?;
 
+    Ok(())
+}
+
+/// Create the write-ahead channel checkpoint table.
+fn create_channel_checkpoint_tables(conn: &Connection) -> Result<()> {
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_manifests_version_root ON manifests(version_root)",
+        "CREATE TABLE IF NOT EXISTS channel_checkpoints (
+            channel_id BLOB NOT NULL,
+            nonce INTEGER NOT NULL,
+            peer_id BLOB NOT NULL,
+            my_balance INTEGER NOT NULL,
+            their_balance INTEGER NOT NULL,
+            signature BLOB NOT NULL,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY (channel_id, nonce)
+        )",
         [],
     )?;
 
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_manifests_created ON manifests(created_at)",
+        "CREATE INDEX IF NOT EXISTS idx_channel_checkpoints_peer ON channel_checkpoints(peer_id)",
         [],
     )?;
 
+    Ok(())
+}
+
+/// Create the per-channel payment nonce table.
+fn create_payment_nonce_tables(conn: &Connection) -> Result<()> {
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_manifests_owner ON manifests(owner)",
+        "CREATE TABLE IF NOT EXISTS payment_nonces (
+            channel_id BLOB PRIMARY KEY,
+            peer_id BLOB NOT NULL,
+            last_sent INTEGER NOT NULL,
+            last_received INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        )",
         [],
     )?;
 
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_manifests_content_type ON manifests(content_type)",
+        "CREATE INDEX IF NOT EXISTS idx_payment_nonces_peer ON payment_nonces(peer_id)",
         [],
     )?;
 
-    // Provenance forward edges table
+    Ok(())
+}
+
+/// Create the per-content access log table.
+fn create_access_log_tables(conn: &Connection) -> Result<()> {
     conn.execute(
-        "CREATE TABLE IF NOT EXISTS derived_from (
+        "CREATE TABLE IF NOT EXISTS content_access (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
             content_hash BLOB NOT NULL,
-            source_hash BLOB NOT NULL,
-            PRIMARY KEY (content_hash, source_hash)
+            requester BLOB NOT NULL,
+            requester_hashed INTEGER NOT NULL,
+            kind INTEGER NOT NULL,
+            amount INTEGER NOT NULL,
+            timestamp INTEGER NOT NULL
         )",
         [],
     )?;
 
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_derived_from_source ON derived_from(source_hash)",
+        "CREATE INDEX IF NOT EXISTS idx_content_access_hash ON content_access(content_hash, timestamp)",
         [],
     )?;
-
-    // Cached flattened roots table
     conn.execute(
-        "CREATE TABLE IF NOT EXISTS root_cache (
-            content_hash BLOB NOT NULL,
-            root_hash BLOB NOT NULL,
-            owner BLOB NOT NULL,
-            visibility INTEGER NOT NULL,
-            weight INTEGER NOT NULL DEFAULT 1,
-            PRIMARY KEY (content_hash, root_hash)
-        )",
+        "CREATE INDEX IF NOT EXISTS idx_content_access_time ON content_access(timestamp)",
         [],
     )?;
 
-    // Payment channels table
+    Ok(())
+}
+
+/// Create the per-hash popularity counter table.
+fn create_popularity_tables(conn: &Connection) -> Result<()> {
     conn.execute(
-        "CREATE TABLE IF NOT EXISTS channels (
-            peer_id BLOB PRIMARY KEY,
-            channel_id BLOB NOT NULL,
-            state INTEGER NOT NULL,
-            my_balance INTEGER NOT NULL,
-            their_balance INTEGER NOT NULL,
-            nonce INTEGER NOT NULL,
-            last_update INTEGER NOT NULL,
-            pending_close TEXT,
-            pending_dispute TEXT,
-            funding_tx_id TEXT
+        "CREATE TABLE IF NOT EXISTS popularity (
+            hash BLOB PRIMARY KEY,
+            previews INTEGER NOT NULL,
+            queries INTEGER NOT NULL,
+            search_hits INTEGER NOT NULL,
+            score REAL NOT NULL,
+            updated_at INTEGER NOT NULL
         )",
         [],
     )?;
 
-    // Pending payments table
+    Ok(())
+}
+
+/// Create the usage report table.
+///
+/// Reports are stored without the reporting peer; only the per-content
+/// figures are kept.
+fn create_usage_report_tables(conn: &Connection) -> Result<()> {
     conn.execute(
-        "CREATE TABLE IF NOT EXISTS payments (
-            id BLOB PRIMARY KEY,
-            channel_peer BLOB NOT NULL,
-            channel_id BLOB NOT NULL,
-            amount INTEGER NOT NULL,
-            recipient BLOB NOT NULL,
-            query_hash BLOB NOT NULL,
-            provenance TEXT NOT NULL,
-            timestamp INTEGER NOT NULL,
-            signature BLOB NOT NULL,
-            settled INTEGER NOT NULL DEFAULT 0
+        "CREATE TABLE IF NOT EXISTS usage_reports (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            content_hash BLOB NOT NULL,
+            bytes_read INTEGER NOT NULL,
+            context_tokens INTEGER,
+            rating INTEGER,
+            timestamp INTEGER NOT NULL
         )",
         [],
     )?;
 
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_payments_channel ON payments(channel_peer)",
+        "CREATE INDEX IF NOT EXISTS idx_usage_reports_hash ON usage_reports(content_hash, timestamp)",
         [],
     )?;
 
+    Ok(())
+}
+
+/// Create the metadata schema cache table.
+fn create_metadata_schema_tables(conn: &Connection) -> Result<()> {
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_payments_settled ON payments(settled)",
+        "CREATE TABLE IF NOT EXISTS metadata_schemas (
+            uri TEXT PRIMARY KEY,
+            schema TEXT NOT NULL
+        )",
         [],
     )?;
 
-    // Peers table
+    Ok(())
+}
+
+/// Create the tag registry table.
+fn create_tag_tables(conn: &Connection) -> Result<()> {
     conn.execute(
-        "CREATE TABLE IF NOT EXISTS peers (
-            peer_id BLOB PRIMARY KEY,
-            public_key BLOB NOT NULL,
-            addresses TEXT NOT NULL,
-            last_seen INTEGER NOT NULL,
-            reputation INTEGER NOT NULL DEFAULT 0
+        "CREATE TABLE IF NOT EXISTS tags (
+            tag TEXT PRIMARY KEY,
+            parent TEXT
         )",
         [],
     )?;
-
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_peers_last_seen ON peers(last_seen)",
+        "CREATE INDEX IF NOT EXISTS idx_tags_parent ON tags(parent)",
         [],
     )?;
 
+    Ok(())
+}
+
+/// Create the double-entry economic ledger tables.
+fn create_ledger_tables(conn: &Connection) -> Result<()> {
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_peers_reputation ON peers(reputation)",
+        "CREATE TABLE IF NOT EXISTS ledger_transactions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            event TEXT NOT NULL,
+            reference TEXT,
+            timestamp INTEGER NOT NULL
+        )",
         [],
     )?;
 
-    // Cache metadata table (content stored on filesystem)
     conn.execute(
-        "CREATE TABLE IF NOT EXISTS cache (
-            hash BLOB PRIMARY KEY,
-            source_peer BLOB NOT NULL,
-            queried_at INTEGER NOT NULL,
-            size_bytes INTEGER NOT NULL,
-            payment_receipt TEXT NOT NULL
+        "CREATE TABLE IF NOT EXISTS ledger_entries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            transaction_id INTEGER NOT NULL REFERENCES ledger_transactions(id),
+            account TEXT NOT NULL,
+            debit INTEGER NOT NULL,
+            credit INTEGER NOT NULL,
+            balance INTEGER NOT NULL
         )",
         [],
     )?;
 
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_cache_queried ON cache(queried_at)",
+        "CREATE INDEX IF NOT EXISTS idx_ledger_entries_account ON ledger_entries(account, id)",
         [],
     )?;
 
-    // Settlement queue table
+    Ok(())
+}
+
+/// Create the invoice table.
+fn create_invoice_tables(conn: &Connection) -> Result<()> {
     conn.execute(
-        "CREATE TABLE IF NOT EXISTS settlement_queue (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            payment_id BLOB NOT NULL,
-            recipient BLOB NOT NULL,
+        "CREATE TABLE IF NOT EXISTS invoices (
+            hash BLOB PRIMARY KEY,
+            payee BLOB NOT NULL,
+            payee_key BLOB NOT NULL,
             amount INTEGER NOT NULL,
-            source_hash BLOB NOT NULL,
-            queued_at INTEGER NOT NULL,
-            settled INTEGER NOT NULL DEFAULT 0,
-            batch_id BLOB
+            memo TEXT NOT NULL,
+            reference BLOB,
+            created_at INTEGER NOT NULL,
+            expires_at INTEGER NOT NULL,
+            signature BLOB NOT NULL,
+            direction TEXT NOT NULL,
+            status TEXT NOT NULL,
+            payer BLOB,
+            payment_id BLOB,
+            paid_at INTEGER
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_invoices_created ON invoices(created_at)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the group membership table.
+fn create_group_tables(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS groups (
+            id BLOB PRIMARY KEY,
+            owner BLOB NOT NULL,
+            name TEXT NOT NULL,
+            version INTEGER NOT NULL,
+            data TEXT NOT NULL,
+            fetched_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the content encryption key table.
+fn create_content_key_tables(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS content_keys (
+            hash BLOB PRIMARY KEY,
+            key BLOB NOT NULL,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the canonical version pointer table.
+fn create_canonical_version_tables(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS canonical_versions (
+            version_root BLOB PRIMARY KEY,
+            data TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the cross-device sync tables.
+fn create_sync_tables(conn: &Connection) -> Result<()> {
+    // Bundles held for other devices' owners
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_bundles (
+            owner BLOB PRIMARY KEY,
+            created_at INTEGER NOT NULL,
+            ciphertext BLOB NOT NULL
+        )",
+        [],
+    )?;
+
+    // Channel nonces as of the last sync
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_channel_bases (
+            channel_id BLOB PRIMARY KEY,
+            nonce INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    // Query receipts imported from other devices
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_receipts (
+            payment_id BLOB PRIMARY KEY,
+            data TEXT NOT NULL,
+            queried_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the replica delegation table.
+fn create_replica_tables(conn: &Connection) -> Result<()> {
+    // Delegations held from primaries whose catalogs we serve
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS replica_delegations (
+            primary_peer BLOB PRIMARY KEY,
+            data TEXT NOT NULL,
+            expires_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the webhook delivery table.
+fn create_webhook_tables(conn: &Connection) -> Result<()> {
+    // One row per event and endpoint, kept after delivery as a record
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS webhook_deliveries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            url TEXT NOT NULL,
+            event TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            status TEXT NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            next_attempt_at INTEGER NOT NULL,
+            last_error TEXT,
+            created_at INTEGER NOT NULL,
+            finished_at INTEGER
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due
+         ON webhook_deliveries(status, next_attempt_at)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the activity digest tables.
+fn create_digest_tables(conn: &Connection) -> Result<()> {
+    // Settlement batches that failed on-chain, reported in digests
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS settlement_failures (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            batch_id BLOB NOT NULL,
+            amount INTEGER NOT NULL,
+            error TEXT NOT NULL,
+            timestamp INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_settlement_failures_time
+         ON settlement_failures(timestamp)",
+        [],
+    )?;
+
+    // End of the last period a digest was sent for
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS digest_runs (
+            period TEXT PRIMARY KEY,
+            sent_through INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the standing order and standing order purchase tables.
+fn create_standing_order_tables(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS standing_orders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            tag TEXT NOT NULL,
+            max_price INTEGER NOT NULL,
+            min_reputation INTEGER,
+            budget INTEGER,
+            spent INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS standing_order_purchases (
+            content_hash BLOB PRIMARY KEY,
+            order_id INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            amount INTEGER NOT NULL,
+            purchased_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_standing_order_purchases_order
+         ON standing_order_purchases(order_id, purchased_at)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the content availability table.
+fn create_availability_tables(conn: &Connection) -> Result<()> {
+    // Latest availability check per content hash, with running totals
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS availability_checks (
+            hash BLOB PRIMARY KEY,
+            checker BLOB NOT NULL,
+            reachable INTEGER NOT NULL,
+            dialed TEXT,
+            latency_ms INTEGER,
+            error TEXT,
+            checked_at INTEGER NOT NULL,
+            checks INTEGER NOT NULL,
+            failures INTEGER NOT NULL,
+            consecutive_failures INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the tenant tables.
+fn create_tenant_tables(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tenants (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            key_hash BLOB NOT NULL UNIQUE,
+            budget INTEGER,
+            spent INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    // Purchases made on behalf of a tenant, keyed by payment receipt
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tenant_purchases (
+            payment_id BLOB PRIMARY KEY,
+            tenant TEXT NOT NULL,
+            content_hash BLOB NOT NULL,
+            amount INTEGER NOT NULL,
+            purchased_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_tenant_purchases_tenant
+         ON tenant_purchases(tenant, purchased_at)",
+        [],
+    )?;
+
+    // Our content published on behalf of a tenant, for earnings attribution
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tenant_content (
+            content_hash BLOB PRIMARY KEY,
+            tenant TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the tombstone table.
+fn create_tombstone_tables(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tombstones (
+            content_hash BLOB PRIMARY KEY,
+            owner BLOB NOT NULL,
+            data TEXT NOT NULL,
+            received_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the content report and moderation tables.
+fn create_moderation_tables(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS content_reports (
+            content_hash BLOB NOT NULL,
+            reporter BLOB NOT NULL,
+            reason TEXT NOT NULL,
+            data TEXT NOT NULL,
+            received_at INTEGER NOT NULL,
+            PRIMARY KEY (content_hash, reporter)
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS moderation (
+            content_hash BLOB PRIMARY KEY,
+            status TEXT NOT NULL,
+            updated_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_moderation_status ON moderation(status)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the key revocation table.
+fn create_revocation_tables(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS revocations (
+            peer_id BLOB PRIMARY KEY,
+            data TEXT NOT NULL,
+            received_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the fraud proof table.
+fn create_fraud_proof_tables(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS fraud_proofs (
+            hash BLOB PRIMARY KEY,
+            provider BLOB NOT NULL,
+            data TEXT NOT NULL,
+            received_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_fraud_proofs_provider ON fraud_proofs(provider)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the daily quota usage table.
+fn create_quota_tables(conn: &Connection) -> Result<()> {
+    // Usage per peer and UTC day, per content hash; an empty content hash
+    // holds the peer's node-wide usage
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS quota_usage (
+            peer_id BLOB NOT NULL,
+            content_hash BLOB NOT NULL,
+            period INTEGER NOT NULL,
+            queries INTEGER NOT NULL,
+            bytes INTEGER NOT NULL,
+            PRIMARY KEY (peer_id, content_hash, period)
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the purchase library table.
+fn create_library_tables(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS library (
+            hash BLOB PRIMARY KEY,
+            title TEXT NOT NULL,
+            content_type INTEGER NOT NULL,
+            price_paid INTEGER NOT NULL,
+            provider BLOB NOT NULL,
+            purchased_at INTEGER NOT NULL,
+            receipt TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_library_purchased ON library(purchased_at)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the re-download entitlement table.
+fn create_entitlement_tables(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS entitlements (
+            payment_id BLOB PRIMARY KEY,
+            content_hash BLOB NOT NULL,
+            peer_id BLOB NOT NULL,
+            amount INTEGER NOT NULL,
+            granted_at INTEGER NOT NULL,
+            redemptions INTEGER NOT NULL DEFAULT 0,
+            last_redeemed_at INTEGER
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_entitlements_granted ON entitlements(granted_at)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the channel dispute table.
+fn create_dispute_tables(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS disputes (
+            channel_id BLOB PRIMARY KEY,
+            peer_id BLOB NOT NULL,
+            opened_by_us INTEGER NOT NULL,
+            started_at INTEGER NOT NULL,
+            deadline INTEGER NOT NULL,
+            disputed_nonce INTEGER NOT NULL,
+            counter_tx_id TEXT,
+            warned_at INTEGER,
+            closed_at INTEGER
         )",
         [],
     )?;
 
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_settlement_queue_recipient ON settlement_queue(recipient)",
-        [],
-    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_disputes_deadline ON disputes(deadline)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create the free trial read table.
+fn create_trial_tables(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS trial_reads (
+            content_hash BLOB NOT NULL,
+            peer_id BLOB NOT NULL,
+            reads INTEGER NOT NULL,
+            last_read_at INTEGER NOT NULL,
+            PRIMARY KEY (content_hash, peer_id)
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Create all database tables.
+fn create_tables(conn: &Connection) -> Result<()> {
+    // Manifests table
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS manifests (
+            hash BLOB PRIMARY KEY,
+            content_type INTEGER NOT NULL,
+            owner BLOB NOT NULL,
+            version_number INTEGER NOT NULL,
+            version_previous BLOB,
+            version_root BLOB NOT NULL,
+            version_timestamp INTEGER NOT NULL,
+            visibility INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            description TEXT,
+            tags TEXT,
+            content_size INTEGER NOT NULL,
+            mime_type TEXT,
+            price INTEGER NOT NULL,
+            total_queries INTEGER NOT NULL DEFAULT 0,
+            total_revenue INTEGER NOT NULL DEFAULT 0,
+            access_control TEXT NOT NULL,
+            provenance TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            metadata_schema TEXT,
+            metadata_fields TEXT,
+            preview_policy TEXT,
+            bond TEXT,
+            chunks TEXT,
+            version_forked INTEGER NOT NULL DEFAULT 0,
+            pricing TEXT,
+            sections TEXT,
+            metering TEXT,
+            referral TEXT
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_manifests_visibility ON manifests(visibility)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_manifests_version_root ON manifests(version_root)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_manifests_created ON manifests(created_at)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_manifests_owner ON manifests(owner)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_manifests_content_type ON manifests(content_type)",
+        [],
+    )?;
+
+    // Provenance forward edges table
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS derived_from (
+            content_hash BLOB NOT NULL,
+            source_hash BLOB NOT NULL,
+            PRIMARY KEY (content_hash, source_hash)
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_derived_from_source ON derived_from(source_hash)",
+        [],
+    )?;
+
+    // Cached flattened roots table
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS root_cache (
+            content_hash BLOB NOT NULL,
+            root_hash BLOB NOT NULL,
+            owner BLOB NOT NULL,
+            visibility INTEGER NOT NULL,
+            weight INTEGER NOT NULL DEFAULT 1,
+            PRIMARY KEY (content_hash, root_hash)
+        )",
+        [],
+    )?;
+
+    // Payment channels table
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS channels (
+            peer_id BLOB PRIMARY KEY,
+            channel_id BLOB NOT NULL,
+            state INTEGER NOT NULL,
+            my_balance INTEGER NOT NULL,
+            their_balance INTEGER NOT NULL,
+            nonce INTEGER NOT NULL,
+            last_update INTEGER NOT NULL,
+            pending_close TEXT,
+            pending_dispute TEXT,
+            funding_tx_id TEXT
+        )",
+        [],
+    )?;
+
+    // Pending payments table
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS payments (
+            id BLOB PRIMARY KEY,
+            channel_peer BLOB NOT NULL,
+            channel_id BLOB NOT NULL,
+            amount INTEGER NOT NULL,
+            recipient BLOB NOT NULL,
+            query_hash BLOB NOT NULL,
+            provenance TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            signature BLOB NOT NULL,
+            settled INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_payments_channel ON payments(channel_peer)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_payments_settled ON payments(settled)",
+        [],
+    )?;
+
+    // Peers table
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS peers (
+            peer_id BLOB PRIMARY KEY,
+            public_key BLOB NOT NULL,
+            addresses TEXT NOT NULL,
+            last_seen INTEGER NOT NULL,
+            reputation INTEGER NOT NULL DEFAULT 0,
+            capabilities INTEGER,
+            latency TEXT
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_peers_last_seen ON peers(last_seen)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_peers_reputation ON peers(reputation)",
+        [],
+    )?;
+
+    // Cache metadata table (content stored on filesystem)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS cache (
+            hash BLOB PRIMARY KEY,
+            source_peer BLOB NOT NULL,
+            queried_at INTEGER NOT NULL,
+            size_bytes INTEGER NOT NULL,
+            payment_receipt TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_cache_queried ON cache(queried_at)",
+        [],
+    )?;
+
+    // Settlement queue table
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS settlement_queue (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            payment_id BLOB NOT NULL,
+            recipient BLOB NOT NULL,
+            amount INTEGER NOT NULL,
+            source_hash BLOB NOT NULL,
+            queued_at INTEGER NOT NULL,
+            settled INTEGER NOT NULL DEFAULT 0,
+            batch_id BLOB
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_settlement_queue_recipient ON settlement_queue(recipient)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_settlement_queue_settled ON settlement_queue(settled)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_settlement_queue_payment_id ON settlement_queue(payment_id)",
+        [],
+    )?;
+
+    // Settlement metadata table
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS settlement_meta (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // Wallet transaction history
+    create_wallet_tables(conn)?;
+
+    // Write-ahead channel checkpoints
+    create_channel_checkpoint_tables(conn)?;
+    create_payment_nonce_tables(conn)?;
+    create_access_log_tables(conn)?;
+    create_usage_report_tables(conn)?;
+    create_popularity_tables(conn)?;
+    create_metadata_schema_tables(conn)?;
+    create_tag_tables(conn)?;
+    create_ledger_tables(conn)?;
+    create_invoice_tables(conn)?;
+    create_group_tables(conn)?;
+    create_content_key_tables(conn)?;
+    create_tombstone_tables(conn)?;
+    create_moderation_tables(conn)?;
+    create_revocation_tables(conn)?;
+    create_fraud_proof_tables(conn)?;
+    create_trial_tables(conn)?;
+    create_canonical_version_tables(conn)?;
+    create_sync_tables(conn)?;
+    create_replica_tables(conn)?;
+    create_webhook_tables(conn)?;
+    create_digest_tables(conn)?;
+    create_standing_order_tables(conn)?;
+    create_availability_tables(conn)?;
+    create_tenant_tables(conn)?;
+    create_quota_tables(conn)?;
+    create_library_tables(conn)?;
+    create_entitlement_tables(conn)?;
+    create_dispute_tables(conn)?;
+
+    // L1 summaries table
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS l1_summaries (
+            l0_hash BLOB PRIMARY KEY,
+            mention_count INTEGER NOT NULL,
+            preview_mentions TEXT NOT NULL,
+            primary_topics TEXT NOT NULL,
+            summary TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // Announcements table (content discovered from network)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS announcements (
+            hash BLOB PRIMARY KEY,
+            content_type INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            l1_summary TEXT NOT NULL,
+            price INTEGER NOT NULL,
+            addresses TEXT NOT NULL,
+            received_at INTEGER NOT NULL,
+            publisher_peer_id TEXT,
+            publisher_key BLOB,
+            signature BLOB
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_announcements_received ON announcements(received_at)",
+        [],
+    )?;
+
+    // Migration: Add publisher_peer_id column if it doesn't exist (for existing DBs)
+    // SQLite doesn't have IF NOT EXISTS for ALTER TABLE, so we check first
+    let has_publisher_peer_id: bool = conn
+        .prepare("SELECT publisher_peer_id FROM announcements LIMIT 1")
+        .is_ok();
+    if !has_publisher_peer_id {
+        if let Err(e) = conn.execute(
+            "ALTER TABLE announcements ADD COLUMN publisher_peer_id TEXT",
+            [],
+        ) {
+            // Column may already exist from a concurrent migration - only warn for unexpected errors
+            if !e.to_string().contains("duplicate column") {
+                tracing::warn!(error = %e, "Failed to add publisher_peer_id column to announcements");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    #[test]
+    fn test_initialize_schema() {
+        let conn = Connection::open_in_memory().unwrap();
+        let result = initialize_schema(&conn);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_initialize_schema_idempotent() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        // First initialization
+        initialize_schema(&conn).unwrap();
+
+        // Second initialization should succeed
+        let result = initialize_schema(&conn);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_tables_exist() {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+
+        // Verify tables exist by querying their structure
+        let tables = [
+            "manifests",
+            "derived_from",
+            "root_cache",
+            "channels",
+            "payments",
+            "peers",
+            "cache",
+            "settlement_queue",
+            "settlement_meta",
+            "wallet_transactions",
+            "channel_checkpoints",
+            "payment_nonces",
+            "invoices",
+            "groups",
+            "content_keys",
+            "tombstones",
+            "content_reports",
+            "moderation",
+            "revocations",
+            "fraud_proofs",
+            "trial_reads",
+            "canonical_versions",
+            "sync_bundles",
+            "sync_channel_bases",
+            "sync_receipts",
+            "replica_delegations",
+            "webhook_deliveries",
+            "settlement_failures",
+            "digest_runs",
+            "standing_orders",
+            "standing_order_purchases",
+            "availability_checks",
+            "tenants",
+            "tenant_purchases",
+            "tenant_content",
+            "quota_usage",
+            "library",
+            "entitlements",
+            "disputes",
+            "content_access",
+            "usage_reports",
+            "popularity",
+            "metadata_schemas",
+            "l1_summaries",
+        ];
+
+        for table in tables {
+            let exists: i32 = conn
+                .query_row(
+                    &format!(
+                        "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='{}'",
+                        table
+                    ),
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(exists, 1, "Table {} should exist", table);
+        }
+    }
+
+    #[test]
+    fn test_schema_version() {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+
+        let version: u32 = conn
+            .query_row("SELECT version FROM schema_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_migration_v2_to_v3() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        // Simulate a v2 database by creating tables and setting version to 2
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (2)", [])
+            .unwrap();
+
+        // Create the channels table WITHOUT funding_tx_id (v2 schema)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS channels (
+                peer_id BLOB PRIMARY KEY,
+                channel_id BLOB NOT NULL,
+                state INTEGER NOT NULL,
+                my_balance INTEGER NOT NULL,
+                their_balance INTEGER NOT NULL,
+                nonce INTEGER NOT NULL,
+                last_update INTEGER NOT NULL,
+                pending_close TEXT,
+                pending_dispute TEXT
+            )",
+            [],
+        )
+        .unwrap();
+
+        // Run migration
+        initialize_schema(&conn).unwrap();
+
+        // Verify version was bumped
+        let version: u32 = conn
+            .query_row("SELECT version FROM schema_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+
+        // Verify funding_tx_id column exists by querying table_info
+        let has_column: bool = conn
+            .prepare("PRAGMA table_info(channels)")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(1))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .any(|name| name == "funding_tx_id");
+        assert!(
+            has_column,
+            "funding_tx_id column should exist after migration"
+        );
+    }
+
+    #[test]
+    fn test_migration_v4_to_v5() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        // Simulate a v4 database
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (4)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let version: u32 = conn
+            .query_row("SELECT version FROM schema_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='channel_checkpoints'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
+
+    #[test]
+    fn test_migration_v5_to_v6() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        // Simulate a v5 database
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (5)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let version: u32 = conn
+            .query_row("SELECT version FROM schema_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='content_access'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
+
+    #[test]
+    fn test_migration_v6_to_v7() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        // Simulate a v6 database with the old manifests table
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (6)", [])
+            .unwrap();
+        conn.execute(
+            "CREATE TABLE manifests (hash BLOB PRIMARY KEY, title TEXT NOT NULL)",
+            [],
+        )
+        .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(manifests)")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(1))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert!(columns.contains(&"metadata_schema".to_string()));
+        assert!(columns.contains(&"metadata_fields".to_string()));
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='metadata_schemas'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
+
+    #[test]
+    fn test_migration_v7_to_v8() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        // Simulate a v7 database with the old announcements table
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (7)", [])
+            .unwrap();
+        conn.execute(
+            "CREATE TABLE announcements (hash BLOB PRIMARY KEY, publisher_peer_id TEXT)",
+            [],
+        )
+        .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(announcements)")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(1))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert!(columns.contains(&"publisher_key".to_string()));
+        assert!(columns.contains(&"signature".to_string()));
+    }
+
+    #[test]
+    fn test_migration_v8_to_v9() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (8)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='tags'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
+
+    #[test]
+    fn test_migration_v9_to_v10() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (9)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name IN ('ledger_transactions', 'ledger_entries')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 2);
+    }
+
+    #[test]
+    fn test_migration_v10_to_v11() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (10)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='payment_nonces'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
+
+    #[test]
+    fn test_migration_v11_to_v12() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (11)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='invoices'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
+
+    #[test]
+    fn test_migration_v12_to_v13() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (12)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='usage_reports'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
+
+    #[test]
+    fn test_migration_v13_to_v14() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (13)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='groups'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
+
+    #[test]
+    fn test_migration_v14_to_v15() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (14)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='content_keys'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
+
+    #[test]
+    fn test_migration_v15_to_v16() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (15)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='tombstones'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
+
+    #[test]
+    fn test_migration_v16_to_v17() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (16)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        for table in ["content_reports", "moderation"] {
+            let exists: i32 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name=?1",
+                    [table],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(exists, 1, "missing table {}", table);
+        }
+    }
+
+    #[test]
+    fn test_migration_v17_to_v18() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        // Simulate a v17 database with the old manifests table
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (17)", [])
+            .unwrap();
+        conn.execute(
+            "CREATE TABLE manifests (hash BLOB PRIMARY KEY, title TEXT NOT NULL)",
+            [],
+        )
+        .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(manifests)")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(1))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert!(columns.contains(&"preview_policy".to_string()));
+    }
+
+    #[test]
+    fn test_migration_v18_to_v19() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (18)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='revocations'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
+
+    #[test]
+    fn test_migration_v19_to_v20() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (19)", [])
+            .unwrap();
+        conn.execute(
+            "CREATE TABLE manifests (hash BLOB PRIMARY KEY, title TEXT NOT NULL)",
+            [],
+        )
+        .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(manifests)")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(1))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert!(columns.contains(&"bond".to_string()));
+    }
+
+    #[test]
+    fn test_migration_v20_to_v21() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (20)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='fraud_proofs'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
+
+    #[test]
+    fn test_migration_v21_to_v22() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (21)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='popularity'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
+
+    #[test]
+    fn test_migration_v22_to_v23() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (22)", [])
+            .unwrap();
+        conn.execute(
+            "CREATE TABLE manifests (hash BLOB PRIMARY KEY, title TEXT NOT NULL)",
+            [],
+        )
+        .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(manifests)")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(1))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert!(columns.contains(&"chunks".to_string()));
+    }
+
+    #[test]
+    fn test_migration_v23_to_v24() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (23)", [])
+            .unwrap();
+        conn.execute(
+            "CREATE TABLE peers (peer_id BLOB PRIMARY KEY, public_key BLOB NOT NULL)",
+            [],
+        )
+        .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(peers)")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(1))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert!(columns.contains(&"capabilities".to_string()));
+    }
+
+    #[test]
+    fn test_migration_v24_to_v25() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (24)", [])
+            .unwrap();
+        conn.execute(
+            "CREATE TABLE manifests (hash BLOB PRIMARY KEY, title TEXT NOT NULL)",
+            [],
+        )
+        .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(manifests)")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(1))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert!(columns.contains(&"version_forked".to_string()));
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='canonical_versions'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
+
+    #[test]
+    fn test_migration_v25_to_v26() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (25)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        for table in ["sync_bundles", "sync_channel_bases", "sync_receipts"] {
+            let exists: i32 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name=?1",
+                    [table],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(exists, 1, "missing table {}", table);
+        }
+    }
+
+    #[test]
+    fn test_migration_v26_to_v27() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (26)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='replica_delegations'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
+
+    #[test]
+    fn test_migration_v27_to_v28() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (27)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='webhook_deliveries'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
+
+    #[test]
+    fn test_migration_v28_to_v29() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (28)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        for name in [
+            "settlement_failures",
+            "digest_runs",
+            "idx_content_access_time",
+        ] {
+            let exists: i32 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE name = ?1",
+                    [name],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(exists, 1, "{} missing", name);
+        }
+    }
+
+    #[test]
+    fn test_migration_v29_to_v30() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (29)", [])
+            .unwrap();
+        conn.execute(
+            "CREATE TABLE manifests (hash BLOB PRIMARY KEY, title TEXT NOT NULL)",
+            [],
+        )
+        .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(manifests)")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(1))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert!(columns.contains(&"pricing".to_string()));
+    }
+
+    #[test]
+    fn test_migration_v30_to_v31() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (30)", [])
+            .unwrap();
+        conn.execute(
+            "CREATE TABLE manifests (hash BLOB PRIMARY KEY, title TEXT NOT NULL)",
+            [],
+        )
+        .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(manifests)")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(1))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert!(columns.contains(&"sections".to_string()));
+    }
+
+    #[test]
+    fn test_migration_v31_to_v32() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (31)", [])
+            .unwrap();
+        conn.execute(
+            "CREATE TABLE manifests (hash BLOB PRIMARY KEY, title TEXT NOT NULL)",
+            [],
+        )
+        .unwrap();
+
+        initialize_schema(&conn).unwrap();
 
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_settlement_queue_settled ON settlement_queue(settled)",
-        [],
-    )?;
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(manifests)")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(1))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert!(columns.contains(&"metering".to_string()));
+    }
 
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_settlement_queue_payment_id ON settlement_queue(payment_id)",
-        [],
-    )?;
+    #[test]
+    fn test_migration_v32_to_v33() {
+        let conn = Connection::open_in_memory().unwrap();
 
-    // Settlement metadata table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS settlement_meta (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL
-        )",
-        [],
-    )?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (32)", [])
+            .unwrap();
 
-    // L1 summaries table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS l1_summaries (
-            l0_hash BLOB PRIMARY KEY,
-            mention_count INTEGER NOT NULL,
-            preview_mentions TEXT NOT NULL,
-            primary_topics TEXT NOT NULL,
-            summary TEXT NOT NULL
-        )",
-        [],
-    )?;
+        initialize_schema(&conn).unwrap();
 
-    // Announcements table (content discovered from network)
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS announcements (
-            hash BLOB PRIMARY KEY,
-            content_type INTEGER NOT NULL,
-            title TEXT NOT NULL,
-            l1_summary TEXT NOT NULL,
-            price INTEGER NOT NULL,
-            addresses TEXT NOT NULL,
-            received_at INTEGER NOT NULL,
-            publisher_peer_id TEXT
-        )",
-        [],
-    )?;
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='trial_reads'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
 
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_announcements_received ON announcements(received_at)",
-        [],
-    )?;
+    #[test]
+    fn test_migration_v33_to_v34() {
+        let conn = Connection::open_in_memory().unwrap();
 
-    // Migration: Add publisher_peer_id column if it doesn't exist (for existing DBs)
-    // SQLite doesn't have IF NOT EXISTS for ALTER TABLE, so we check first
-    let has_publisher_peer_id: bool = conn
-        .prepare("SELECT publisher_peer_id FROM announcements LIMIT 1")
-        .is_ok();
-    if !has_publisher_peer_id {
-        if let Err(e) = conn.execute(
-            "ALTER TABLE announcements ADD COLUMN publisher_peer_id TEXT",
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
             [],
-        ) {
-            // Column may already exist from a concurrent migration - only warn for unexpected errors
-            if !e.to_string().contains("duplicate column") {
-                tracing::warn!(error = %e, "Failed to add publisher_peer_id column to announcements");
-            }
-        }
-    }
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (33)", [])
+            .unwrap();
+        conn.execute(
+            "CREATE TABLE manifests (hash BLOB PRIMARY KEY, title TEXT NOT NULL)",
+            [],
+        )
+        .unwrap();
 
-    Ok(())
-}
+        initialize_schema(&conn).unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use rusqlite::Connection;
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(manifests)")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(1))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert!(columns.contains(&"referral".to_string()));
+    }
 
     #[test]
-    fn test_initialize_schema() {
+    fn test_migration_v34_to_v35() {
         let conn = Connection::open_in_memory().unwrap();
-        let result = initialize_schema(&conn);
-        assert!(result.is_ok());
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (34)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        for table in ["standing_orders", "standing_order_purchases"] {
+            let exists: i32 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name=?1",
+                    [table],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(exists, 1, "missing table {}", table);
+        }
     }
 
     #[test]
-    fn test_initialize_schema_idempotent() {
+    fn test_migration_v35_to_v36() {
         let conn = Connection::open_in_memory().unwrap();
 
-        // First initialization
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (35)", [])
+            .unwrap();
+        conn.execute(
+            "CREATE TABLE peers (peer_id BLOB PRIMARY KEY, public_key BLOB NOT NULL)",
+            [],
+        )
+        .unwrap();
+
         initialize_schema(&conn).unwrap();
 
-        // Second initialization should succeed
-        let result = initialize_schema(&conn);
-        assert!(result.is_ok());
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(peers)")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(1))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert!(columns.contains(&"latency".to_string()));
     }
 
     #[test]
-    fn test_tables_exist() {
+    fn test_migration_v36_to_v37() {
         let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (36)", [])
+            .unwrap();
+
         initialize_schema(&conn).unwrap();
 
-        // Verify tables exist by querying their structure
-        let tables = [
-            "manifests",
-            "derived_from",
-            "root_cache",
-            "channels",
-            "payments",
-            "peers",
-            "cache",
-            "settlement_queue",
-            "settlement_meta",
-            "l1_summaries",
-        ];
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='availability_checks'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
 
-        for table in tables {
+    #[test]
+    fn test_migration_v37_to_v38() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (37)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        for table in ["tenants", "tenant_purchases", "tenant_content"] {
             let exists: i32 = conn
                 .query_row(
-                    &format!(
-                        "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='{}'",
-                        table
-                    ),
-                    [],
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name=?1",
+                    [table],
                     |row| row.get(0),
                 )
                 .unwrap();
-            assert_eq!(exists, 1, "Table {} should exist", table);
+            assert_eq!(exists, 1, "missing table {table}");
         }
     }
 
     #[test]
-    fn test_schema_version() {
+    fn test_migration_v38_to_v39() {
         let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (38)", [])
+            .unwrap();
+
         initialize_schema(&conn).unwrap();
 
-        let version: u32 = conn
-            .query_row("SELECT version FROM schema_version", [], |row| row.get(0))
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='quota_usage'",
+                [],
+                |row| row.get(0),
+            )
             .unwrap();
-        assert_eq!(version, SCHEMA_VERSION);
+        assert_eq!(exists, 1);
     }
 
     #[test]
-    fn test_migration_v2_to_v3() {
+    fn test_migration_v39_to_v40() {
         let conn = Connection::open_in_memory().unwrap();
 
-        // Simulate a v2 database by creating tables and setting version to 2
         conn.execute(
             "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
             [],
         )
         .unwrap();
-        conn.execute("INSERT INTO schema_version (version) VALUES (2)", [])
+        conn.execute("INSERT INTO schema_version (version) VALUES (39)", [])
             .unwrap();
 
-        // Create the channels table WITHOUT funding_tx_id (v2 schema)
+        initialize_schema(&conn).unwrap();
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='library'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
+
+    #[test]
+    fn test_migration_v40_to_v41() {
+        let conn = Connection::open_in_memory().unwrap();
+
         conn.execute(
-            "CREATE TABLE IF NOT EXISTS channels (
-                peer_id BLOB PRIMARY KEY,
-                channel_id BLOB NOT NULL,
-                state INTEGER NOT NULL,
-                my_balance INTEGER NOT NULL,
-                their_balance INTEGER NOT NULL,
-                nonce INTEGER NOT NULL,
-                last_update INTEGER NOT NULL,
-                pending_close TEXT,
-                pending_dispute TEXT
-            )",
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
             [],
         )
         .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (40)", [])
+            .unwrap();
 
-        // Run migration
         initialize_schema(&conn).unwrap();
 
-        // Verify version was bumped
-        let version: u32 = conn
-            .query_row("SELECT version FROM schema_version", [], |row| row.get(0))
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='entitlements'",
+                [],
+                |row| row.get(0),
+            )
             .unwrap();
-        assert_eq!(version, SCHEMA_VERSION);
+        assert_eq!(exists, 1);
+    }
 
-        // Verify funding_tx_id column exists by querying table_info
-        let has_column: bool = conn
-            .prepare("PRAGMA table_info(channels)")
-            .unwrap()
-            .query_map([], |row| row.get::<_, String>(1))
-            .unwrap()
-            .filter_map(|r| r.ok())
-            .any(|name| name == "funding_tx_id");
-        assert!(
-            has_column,
-            "funding_tx_id column should exist after migration"
-        );
+    #[test]
+    fn test_migration_v41_to_v42() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (41)", [])
+            .unwrap();
+
+        initialize_schema(&conn).unwrap();
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='disputes'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exists, 1);
     }
 }
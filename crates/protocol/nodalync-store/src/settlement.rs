@@ -11,7 +11,7 @@ use nodalync_types::Amount;
 
 use crate::error::{Result, StoreError};
 use crate::traits::SettlementQueueStore;
-use crate::types::QueuedDistribution;
+use crate::types::{QueuedDistribution, WalletTransaction, WalletTransactionKind};
 
 /// SQLite-based settlement queue.
 pub struct SqliteSettlementQueue {
@@ -43,7 +43,7 @@ impl SqliteSettlementQueue {
 }
 
 impl SettlementQueueStore for SqliteSettlementQueue {
-    fn enqueue(&mut self, distribution: QueuedDistribution) -> Result<()> {
+    fn enqueue(&self, distribution: QueuedDistribution) -> Result<()> {
         let conn = self
             .conn
             .lock()
@@ -122,7 +122,7 @@ impl SettlementQueueStore for SqliteSettlementQueue {
         Ok(total as Amount)
     }
 
-    fn mark_settled(&mut self, payment_ids: &[Hash], batch_id: &Hash) -> Result<()> {
+    fn mark_settled(&self, payment_ids: &[Hash], batch_id: &Hash) -> Result<()> {
         let conn = self
             .conn
             .lock()
@@ -165,7 +165,7 @@ impl SettlementQueueStore for SqliteSettlementQueue {
         }
     }
 
-    fn set_last_settlement_time(&mut self, timestamp: Timestamp) -> Result<()> {
+    fn set_last_settlement_time(&self, timestamp: Timestamp) -> Result<()> {
         let conn = self
             .conn
             .lock()
@@ -262,7 +262,7 @@ impl SqliteSettlementQueue {
     /// Delete settled distributions older than a timestamp.
     ///
     /// Used for cleanup of old settled records.
-    pub fn cleanup_settled(&mut self, older_than: Timestamp) -> Result<u64> {
+    pub fn cleanup_settled(&self, older_than: Timestamp) -> Result<u64> {
         let conn = self
             .conn
             .lock()
@@ -275,6 +275,97 @@ impl SqliteSettlementQueue {
 
         Ok(deleted as u64)
     }
+
+    /// Record an on-chain transaction submitted by this node.
+    ///
+    /// Re-recording the same transaction ID replaces the earlier entry.
+    pub fn record_transaction(&self, tx: &WalletTransaction) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO wallet_transactions (transaction_id, kind, amount, timestamp)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                tx.transaction_id,
+                tx.kind.as_str(),
+                tx.amount as i64,
+                tx.timestamp as i64,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Get the most recent on-chain transactions, newest first.
+    pub fn recent_transactions(&self, limit: u32) -> Result<Vec<WalletTransaction>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT transaction_id, kind, amount, timestamp FROM wallet_transactions
+             ORDER BY timestamp DESC LIMIT ?1",
+        )?;
+
+        let transactions: Vec<WalletTransaction> = stmt
+            .query_map([limit], |row| {
+                let transaction_id: String = row.get(0)?;
+                let kind: String = row.get(1)?;
+                let amount: i64 = row.get(2)?;
+                let timestamp: i64 = row.get(3)?;
+                Ok((transaction_id, kind, amount, timestamp))
+            })?
+            .filter_map(|r| r.ok())
+            .filter_map(|(transaction_id, kind, amount, timestamp)| {
+                WalletTransactionKind::parse(&kind).map(|kind| WalletTransaction {
+                    transaction_id,
+                    kind,
+                    amount: amount as Amount,
+                    timestamp: timestamp as Timestamp,
+                })
+            })
+            .collect();
+
+        Ok(transactions)
+    }
+
+    /// Get the most recent wallet transaction of a kind.
+    pub fn latest_transaction(
+        &self,
+        kind: WalletTransactionKind,
+    ) -> Result<Option<WalletTransaction>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let row = conn
+            .query_row(
+                "SELECT transaction_id, amount, timestamp FROM wallet_transactions
+                 WHERE kind = ?1 ORDER BY timestamp DESC LIMIT 1",
+                [kind.as_str()],
+                |row| {
+                    let transaction_id: String = row.get(0)?;
+                    let amount: i64 = row.get(1)?;
+                    let timestamp: i64 = row.get(2)?;
+                    Ok((transaction_id, amount, timestamp))
+                },
+            )
+            .optional()?;
+
+        Ok(
+            row.map(|(transaction_id, amount, timestamp)| WalletTransaction {
+                transaction_id,
+                kind,
+                amount: amount as Amount,
+                timestamp: timestamp as Timestamp,
+            }),
+        )
+    }
 }
 
 /// Convert bytes to Hash.
@@ -325,7 +416,7 @@ mod tests {
 
     #[test]
     fn test_enqueue_and_get_pending() {
-        let mut queue = setup_queue();
+        let queue = setup_queue();
         let recipient = test_peer_id();
         let dist = test_distribution(recipient, 100);
 
@@ -339,7 +430,7 @@ mod tests {
 
     #[test]
     fn test_get_pending_for_recipient() {
-        let mut queue = setup_queue();
+        let queue = setup_queue();
 
         let peer1 = test_peer_id();
         let peer2 = test_peer_id();
@@ -357,7 +448,7 @@ mod tests {
 
     #[test]
     fn test_get_pending_total() {
-        let mut queue = setup_queue();
+        let queue = setup_queue();
 
         let peer1 = test_peer_id();
         let peer2 = test_peer_id();
@@ -372,7 +463,7 @@ mod tests {
 
     #[test]
     fn test_mark_settled() {
-        let mut queue = setup_queue();
+        let queue = setup_queue();
         let recipient = test_peer_id();
 
         let dist1 = test_distribution(recipient, 100);
@@ -391,7 +482,7 @@ mod tests {
 
     #[test]
     fn test_settlement_time() {
-        let mut queue = setup_queue();
+        let queue = setup_queue();
 
         // Initially no settlement time
         assert!(queue.get_last_settlement_time().unwrap().is_none());
@@ -411,7 +502,7 @@ mod tests {
 
     #[test]
     fn test_pending_count() {
-        let mut queue = setup_queue();
+        let queue = setup_queue();
         let recipient = test_peer_id();
 
         assert_eq!(queue.pending_count().unwrap(), 0);
@@ -424,7 +515,7 @@ mod tests {
 
     #[test]
     fn test_get_pending_total_for() {
-        let mut queue = setup_queue();
+        let queue = setup_queue();
 
         let peer1 = test_peer_id();
         let peer2 = test_peer_id();
@@ -439,7 +530,7 @@ mod tests {
 
     #[test]
     fn test_aggregate_by_recipient() {
-        let mut queue = setup_queue();
+        let queue = setup_queue();
 
         let peer1 = test_peer_id();
         let peer2 = test_peer_id();
@@ -458,7 +549,7 @@ mod tests {
 
     #[test]
     fn test_get_batch() {
-        let mut queue = setup_queue();
+        let queue = setup_queue();
         let recipient = test_peer_id();
 
         let dist1 = test_distribution(recipient, 100);
@@ -478,7 +569,7 @@ mod tests {
 
     #[test]
     fn test_cleanup_settled() {
-        let mut queue = setup_queue();
+        let queue = setup_queue();
         let recipient = test_peer_id();
 
         let mut dist1 = test_distribution(recipient, 100);
@@ -502,4 +593,52 @@ mod tests {
         let batch = queue.get_batch(&batch_id).unwrap();
         assert_eq!(batch.len(), 1);
     }
+
+    #[test]
+    fn test_recent_transactions() {
+        let queue = setup_queue();
+
+        assert!(queue.recent_transactions(10).unwrap().is_empty());
+
+        queue
+            .record_transaction(&WalletTransaction::new(
+                "tx-1",
+                WalletTransactionKind::Deposit,
+                500,
+                1000,
+            ))
+            .unwrap();
+        queue
+            .record_transaction(&WalletTransaction::new(
+                "tx-2",
+                WalletTransactionKind::Withdraw,
+                200,
+                2000,
+            ))
+            .unwrap();
+        queue
+            .record_transaction(&WalletTransaction::new(
+                "tx-3",
+                WalletTransactionKind::Settlement,
+                300,
+                3000,
+            ))
+            .unwrap();
+
+        let recent = queue.recent_transactions(2).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].transaction_id, "tx-3");
+        assert_eq!(recent[0].kind, WalletTransactionKind::Settlement);
+        assert_eq!(recent[1].transaction_id, "tx-2");
+
+        let latest = queue
+            .latest_transaction(WalletTransactionKind::Withdraw)
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.transaction_id, "tx-2");
+        assert!(queue
+            .latest_transaction(WalletTransactionKind::Bond)
+            .unwrap()
+            .is_none());
+    }
 }
@@ -0,0 +1,310 @@
+//! Standing order storage.
+//!
+//! Standing orders are consumer-side rules for buying announced content
+//! automatically. Each purchase is recorded once per content hash, so the
+//! same content is never bought twice, and counts towards its order's
+//! budget.
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{Hash, Timestamp};
+use nodalync_types::Amount;
+
+use crate::error::{Result, StoreError};
+use crate::traits::StandingOrderStore;
+use crate::types::{StandingOrder, StandingOrderPurchase};
+
+/// Columns selected for a [`StandingOrder`], in `row_to_order` order.
+const ORDER_COLUMNS: &str = "id, tag, max_price, min_reputation, budget, spent, created_at";
+
+/// Columns selected for a [`StandingOrderPurchase`], in `row_to_purchase` order.
+const PURCHASE_COLUMNS: &str = "content_hash, order_id, title, amount, purchased_at";
+
+/// SQLite-based standing order store.
+pub struct SqliteStandingOrderStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStandingOrderStore {
+    /// Create a new standing order store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+}
+
+fn row_to_order(row: &Row<'_>) -> rusqlite::Result<StandingOrder> {
+    Ok(StandingOrder {
+        id: row.get::<_, i64>(0)? as u64,
+        tag: row.get(1)?,
+        max_price: row.get::<_, i64>(2)? as Amount,
+        min_reputation: row.get(3)?,
+        budget: row.get::<_, Option<i64>>(4)?.map(|b| b as Amount),
+        spent: row.get::<_, i64>(5)? as Amount,
+        created_at: row.get::<_, i64>(6)? as Timestamp,
+    })
+}
+
+fn row_to_purchase(row: &Row<'_>) -> rusqlite::Result<StandingOrderPurchase> {
+    let content_hash: Vec<u8> = row.get(0)?;
+    Ok(StandingOrderPurchase {
+        content_hash: bytes_to_hash(&content_hash),
+        order_id: row.get::<_, i64>(1)? as u64,
+        title: row.get(2)?,
+        amount: row.get::<_, i64>(3)? as Amount,
+        purchased_at: row.get::<_, i64>(4)? as Timestamp,
+    })
+}
+
+impl StandingOrderStore for SqliteStandingOrderStore {
+    fn create(
+        &self,
+        tag: &str,
+        max_price: Amount,
+        min_reputation: Option<i64>,
+        budget: Option<Amount>,
+        created_at: Timestamp,
+    ) -> Result<u64> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        conn.execute(
+            "INSERT INTO standing_orders (tag, max_price, min_reputation, budget, spent, created_at)
+             VALUES (?1, ?2, ?3, ?4, 0, ?5)",
+            params![
+                tag,
+                max_price as i64,
+                min_reputation,
+                budget.map(|b| b as i64),
+                created_at as i64
+            ],
+        )?;
+
+        Ok(conn.last_insert_rowid() as u64)
+    }
+
+    fn get(&self, id: u64) -> Result<Option<StandingOrder>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let order = conn
+            .prepare_cached(&format!(
+                "SELECT {} FROM standing_orders WHERE id = ?1",
+                ORDER_COLUMNS
+            ))?
+            .query_row([id as i64], row_to_order)
+            .optional()?;
+
+        Ok(order)
+    }
+
+    fn list(&self) -> Result<Vec<StandingOrder>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM standing_orders ORDER BY created_at ASC, id ASC",
+            ORDER_COLUMNS
+        ))?;
+        let orders = stmt
+            .query_map([], row_to_order)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(orders)
+    }
+
+    fn remove(&self, id: u64) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let deleted = conn.execute("DELETE FROM standing_orders WHERE id = ?1", [id as i64])?;
+
+        Ok(deleted > 0)
+    }
+
+    fn record_purchase(&self, purchase: &StandingOrderPurchase) -> Result<()> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+        let tx = conn.transaction()?;
+
+        let updated = tx.execute(
+            "UPDATE standing_orders SET spent = spent + ?2 WHERE id = ?1",
+            params![purchase.order_id as i64, purchase.amount as i64],
+        )?;
+        if updated == 0 {
+            return Err(StoreError::StandingOrderNotFound(purchase.order_id));
+        }
+        tx.execute(
+            "INSERT INTO standing_order_purchases
+             (content_hash, order_id, title, amount, purchased_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                purchase.content_hash.0.to_vec(),
+                purchase.order_id as i64,
+                purchase.title,
+                purchase.amount as i64,
+                purchase.purchased_at as i64
+            ],
+        )?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn is_purchased(&self, content_hash: &Hash) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let count: i64 = conn
+            .prepare_cached(
+                "SELECT COUNT(*) FROM standing_order_purchases WHERE content_hash = ?1",
+            )?
+            .query_row([content_hash.0.to_vec()], |row| row.get(0))?;
+
+        Ok(count > 0)
+    }
+
+    fn purchases(&self, order_id: Option<u64>, limit: u32) -> Result<Vec<StandingOrderPurchase>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM standing_order_purchases
+             WHERE ?1 IS NULL OR order_id = ?1
+             ORDER BY purchased_at DESC, rowid DESC
+             LIMIT ?2",
+            PURCHASE_COLUMNS
+        ))?;
+        let purchases = stmt
+            .query_map(
+                params![order_id.map(|id| id as i64), limit as i64],
+                row_to_purchase,
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(purchases)
+    }
+}
+
+/// Convert bytes to Hash.
+fn bytes_to_hash(bytes: &[u8]) -> Hash {
+    let mut arr = [0u8; 32];
+    if bytes.len() >= 32 {
+        arr.copy_from_slice(&bytes[..32]);
+    }
+    Hash(arr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::content_hash;
+
+    fn setup_store() -> SqliteStandingOrderStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteStandingOrderStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    fn purchase(
+        order_id: u64,
+        content: &[u8],
+        amount: Amount,
+        at: Timestamp,
+    ) -> StandingOrderPurchase {
+        StandingOrderPurchase {
+            content_hash: content_hash(content),
+            order_id,
+            title: String::from_utf8_lossy(content).into_owned(),
+            amount,
+            purchased_at: at,
+        }
+    }
+
+    #[test]
+    fn test_create_list_and_remove() {
+        let store = setup_store();
+        let first = store.create("science", 100, None, None, 1_000).unwrap();
+        let second = store
+            .create("science/biology", 50, Some(10), Some(500), 2_000)
+            .unwrap();
+
+        let order = store.get(second).unwrap().unwrap();
+        assert_eq!(order.tag, "science/biology");
+        assert_eq!(order.max_price, 50);
+        assert_eq!(order.min_reputation, Some(10));
+        assert_eq!(order.budget, Some(500));
+        assert_eq!(order.spent, 0);
+        assert_eq!(order.created_at, 2_000);
+
+        let ids: Vec<u64> = store.list().unwrap().iter().map(|o| o.id).collect();
+        assert_eq!(ids, vec![first, second]);
+
+        assert!(store.remove(first).unwrap());
+        assert!(!store.remove(first).unwrap());
+        assert!(store.get(first).unwrap().is_none());
+        assert_eq!(store.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_purchases_count_towards_spent() {
+        let store = setup_store();
+        let order = store
+            .create("science", 100, None, Some(250), 1_000)
+            .unwrap();
+        let other = store.create("rust", 0, None, None, 1_000).unwrap();
+
+        store
+            .record_purchase(&purchase(order, b"first", 100, 2_000))
+            .unwrap();
+        store
+            .record_purchase(&purchase(order, b"second", 80, 3_000))
+            .unwrap();
+        store
+            .record_purchase(&purchase(other, b"third", 0, 4_000))
+            .unwrap();
+
+        assert_eq!(store.get(order).unwrap().unwrap().spent, 180);
+        assert!(store.is_purchased(&content_hash(b"first")).unwrap());
+        assert!(!store.is_purchased(&content_hash(b"fourth")).unwrap());
+
+        // The same content is only ever bought once
+        assert!(store
+            .record_purchase(&purchase(other, b"first", 0, 5_000))
+            .is_err());
+        assert_eq!(store.get(order).unwrap().unwrap().spent, 180);
+
+        let titles: Vec<String> = store
+            .purchases(None, 10)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.title)
+            .collect();
+        assert_eq!(titles, vec!["third", "second", "first"]);
+        assert_eq!(store.purchases(Some(order), 10).unwrap().len(), 2);
+        assert_eq!(store.purchases(None, 1).unwrap().len(), 1);
+
+        // Purchases outlive their order
+        assert!(store.remove(order).unwrap());
+        assert_eq!(store.purchases(Some(order), 10).unwrap().len(), 2);
+        assert!(matches!(
+            store.record_purchase(&purchase(order, b"fifth", 10, 6_000)),
+            Err(StoreError::StandingOrderNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,220 @@
+//! Cross-device sync storage.
+//!
+//! Holds what moving state between devices of one identity needs:
+//!
+//! - Encrypted bundles other peers leave with us to be pulled by their
+//!   other devices. Only the newest bundle per owner is kept.
+//! - Each channel's nonce as of the last sync, the common base that tells
+//!   which device advanced a channel since.
+//! - Query receipts imported from other devices, whose content isn't
+//!   cached here.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{Hash, PeerId, Timestamp};
+use nodalync_wire::EncryptedSyncBundle;
+
+use crate::error::{Result, StoreError};
+use crate::traits::SyncStore;
+use crate::types::QueryReceipt;
+
+/// SQLite-based sync store.
+pub struct SqliteSyncStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteSyncStore {
+    /// Create a new sync store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+}
+
+impl SyncStore for SqliteSyncStore {
+    fn put_bundle(&self, bundle: &EncryptedSyncBundle) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let stored = conn.execute(
+            "INSERT INTO sync_bundles (owner, created_at, ciphertext)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(owner) DO UPDATE SET
+                created_at = excluded.created_at,
+                ciphertext = excluded.ciphertext
+             WHERE excluded.created_at > sync_bundles.created_at",
+            params![
+                bundle.owner.0.to_vec(),
+                bundle.created_at as i64,
+                bundle.ciphertext
+            ],
+        )?;
+
+        Ok(stored > 0)
+    }
+
+    fn get_bundle(&self, owner: &PeerId) -> Result<Option<EncryptedSyncBundle>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let bundle = conn
+            .prepare_cached("SELECT created_at, ciphertext FROM sync_bundles WHERE owner = ?1")?
+            .query_row([owner.0.to_vec()], |row| {
+                Ok(EncryptedSyncBundle {
+                    owner: *owner,
+                    created_at: row.get::<_, i64>(0)? as Timestamp,
+                    ciphertext: row.get(1)?,
+                })
+            })
+            .optional()?;
+
+        Ok(bundle)
+    }
+
+    fn set_channel_base(&self, channel_id: &Hash, nonce: u64) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO sync_channel_bases (channel_id, nonce) VALUES (?1, ?2)",
+            params![channel_id.0.to_vec(), nonce as i64],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_channel_base(&self, channel_id: &Hash) -> Result<Option<u64>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let nonce: Option<i64> = conn
+            .prepare_cached("SELECT nonce FROM sync_channel_bases WHERE channel_id = ?1")?
+            .query_row([channel_id.0.to_vec()], |row| row.get(0))
+            .optional()?;
+
+        Ok(nonce.map(|nonce| nonce as u64))
+    }
+
+    fn store_receipt(&self, receipt: &QueryReceipt) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let data = serde_json::to_string(receipt)?;
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO sync_receipts (payment_id, data, queried_at)
+             VALUES (?1, ?2, ?3)",
+            params![
+                receipt.receipt.payment_id.0.to_vec(),
+                data,
+                receipt.queried_at as i64
+            ],
+        )?;
+
+        Ok(inserted > 0)
+    }
+
+    fn list_receipts(&self) -> Result<Vec<QueryReceipt>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare("SELECT data FROM sync_receipts ORDER BY queried_at DESC")?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        rows.iter()
+            .map(|data| Ok(serde_json::from_str(data)?))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::{content_hash, Signature};
+    use nodalync_wire::PaymentReceipt;
+
+    fn setup_store() -> SqliteSyncStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteSyncStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    fn test_bundle(created_at: Timestamp) -> EncryptedSyncBundle {
+        EncryptedSyncBundle {
+            owner: PeerId::from_bytes([7u8; 20]),
+            created_at,
+            ciphertext: created_at.to_be_bytes().to_vec(),
+        }
+    }
+
+    fn test_receipt(payment: &[u8], queried_at: Timestamp) -> QueryReceipt {
+        QueryReceipt {
+            hash: content_hash(b"content"),
+            source_peer: PeerId::from_bytes([1u8; 20]),
+            queried_at,
+            receipt: PaymentReceipt {
+                payment_id: content_hash(payment),
+                amount: 100,
+                timestamp: queried_at,
+                channel_nonce: 1,
+                distributor_signature: Signature::from_bytes([0u8; 64]),
+                app_fee: 0,
+                app_fee_recipient: None,
+                referral_fee: 0,
+                referrer: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_put_bundle_keeps_newest() {
+        let store = setup_store();
+        let owner = test_bundle(0).owner;
+        assert_eq!(store.get_bundle(&owner).unwrap(), None);
+
+        assert!(store.put_bundle(&test_bundle(200)).unwrap());
+        assert!(!store.put_bundle(&test_bundle(100)).unwrap());
+        assert!(!store.put_bundle(&test_bundle(200)).unwrap());
+        assert_eq!(store.get_bundle(&owner).unwrap(), Some(test_bundle(200)));
+
+        assert!(store.put_bundle(&test_bundle(300)).unwrap());
+        assert_eq!(store.get_bundle(&owner).unwrap(), Some(test_bundle(300)));
+    }
+
+    #[test]
+    fn test_channel_base() {
+        let store = setup_store();
+        let channel_id = content_hash(b"channel");
+        assert_eq!(store.get_channel_base(&channel_id).unwrap(), None);
+
+        store.set_channel_base(&channel_id, 3).unwrap();
+        store.set_channel_base(&channel_id, 5).unwrap();
+        assert_eq!(store.get_channel_base(&channel_id).unwrap(), Some(5));
+    }
+
+    #[test]
+    fn test_receipts_newest_first() {
+        let store = setup_store();
+        let first = test_receipt(b"first", 100);
+        let second = test_receipt(b"second", 200);
+
+        assert!(store.store_receipt(&first).unwrap());
+        assert!(store.store_receipt(&second).unwrap());
+        assert!(!store.store_receipt(&first).unwrap());
+        assert_eq!(store.list_receipts().unwrap(), vec![second, first]);
+    }
+}
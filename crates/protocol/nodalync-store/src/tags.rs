@@ -0,0 +1,250 @@
+//! Tag registry.
+//!
+//! This module records the hierarchical tags used by local content. Each
+//! registered tag is stored with its parent, so registering
+//! `science/biology` also registers `science`. Content counts are not
+//! stored; they are computed from the manifests table when tags are read.
+
+use nodalync_types::{normalize_tag, tag_parent, tag_path};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::sync::{Arc, Mutex};
+
+use crate::error::{Result, StoreError};
+use crate::traits::TagStore;
+use crate::types::TagInfo;
+
+/// Columns selected for a [`TagInfo`]: the tag, its parent, and the number
+/// of manifests tagged with it or one of its descendants.
+const TAG_COLUMNS: &str = "t.tag, t.parent, (
+        SELECT COUNT(*) FROM manifests m
+        WHERE EXISTS (
+            SELECT 1 FROM json_each(COALESCE(m.tags, '[]')) j
+            WHERE LOWER(j.value) = t.tag
+                OR substr(LOWER(j.value), 1, length(t.tag) + 1) = t.tag || '/'
+        )
+    )";
+
+/// SQLite-based tag registry.
+pub struct SqliteTagStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteTagStore {
+    /// Create a new tag registry with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+
+    /// Convert a database row to a TagInfo.
+    fn row_to_tag(row: &Row) -> rusqlite::Result<TagInfo> {
+        let count: i64 = row.get(2)?;
+        Ok(TagInfo {
+            tag: row.get(0)?,
+            parent: row.get(1)?,
+            content_count: count as u64,
+        })
+    }
+}
+
+impl TagStore for SqliteTagStore {
+    fn register(&self, tag: &str) -> Result<Option<String>> {
+        let Some(tag) = normalize_tag(tag) else {
+            return Ok(None);
+        };
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        for path_tag in tag_path(&tag) {
+            conn.execute(
+                "INSERT OR IGNORE INTO tags (tag, parent) VALUES (?1, ?2)",
+                params![path_tag, tag_parent(path_tag)],
+            )?;
+        }
+
+        Ok(Some(tag))
+    }
+
+    fn get(&self, tag: &str) -> Result<Option<TagInfo>> {
+        let Some(tag) = normalize_tag(tag) else {
+            return Ok(None);
+        };
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let info = conn
+            .query_row(
+                &format!("SELECT {} FROM tags t WHERE t.tag = ?1", TAG_COLUMNS),
+                [&tag],
+                Self::row_to_tag,
+            )
+            .optional()?;
+
+        Ok(info)
+    }
+
+    fn list(&self) -> Result<Vec<TagInfo>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tags t ORDER BY t.tag ASC",
+            TAG_COLUMNS
+        ))?;
+        let tags = stmt
+            .query_map([], Self::row_to_tag)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(tags)
+    }
+
+    fn autocomplete(&self, prefix: &str, limit: u32) -> Result<Vec<TagInfo>> {
+        // An empty prefix suggests the most used tags
+        let prefix = normalize_tag(prefix).unwrap_or_default();
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tags t
+             WHERE instr('/' || t.tag, '/' || ?1) > 0
+             ORDER BY 3 DESC, t.tag ASC
+             LIMIT ?2",
+            TAG_COLUMNS
+        ))?;
+        let tags = stmt
+            .query_map(params![prefix, limit], Self::row_to_tag)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(tags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::SqliteManifestStore;
+    use crate::schema::initialize_schema;
+    use crate::traits::ManifestStore;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_types::{Manifest, Metadata};
+
+    fn setup() -> (SqliteTagStore, SqliteManifestStore) {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        let conn = Arc::new(Mutex::new(conn));
+        (
+            SqliteTagStore::new(Arc::clone(&conn)),
+            SqliteManifestStore::new(conn),
+        )
+    }
+
+    fn tagged_manifest(content: &[u8], tags: &[&str]) -> Manifest {
+        let (_, public_key) = generate_identity();
+        let metadata = Metadata::new("Tagged", content.len() as u64)
+            .with_tags(tags.iter().map(|t| t.to_string()).collect());
+        Manifest::new_l0(
+            content_hash(content),
+            peer_id_from_public_key(&public_key),
+            metadata,
+            1234567890000,
+        )
+    }
+
+    #[test]
+    fn test_register_hierarchy() {
+        let (tags, _) = setup();
+
+        assert_eq!(
+            tags.register(" Science / Biology ").unwrap().as_deref(),
+            Some("science/biology")
+        );
+        assert_eq!(tags.register(" / ").unwrap(), None);
+
+        let listed: Vec<(String, Option<String>)> = tags
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.tag, t.parent))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("science".to_string(), None),
+                ("science/biology".to_string(), Some("science".to_string())),
+            ]
+        );
+
+        // Re-registering is a no-op
+        tags.register("science").unwrap();
+        assert_eq!(tags.list().unwrap().len(), 2);
+        assert!(tags.get("SCIENCE").unwrap().is_some());
+        assert!(tags.get("physics").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_content_counts_include_descendants() {
+        let (tags, manifests) = setup();
+        for tag in ["science/biology", "science/physics", "sciences"] {
+            tags.register(tag).unwrap();
+        }
+        manifests
+            .store(&tagged_manifest(b"cells", &["science/biology"]))
+            .unwrap();
+        manifests
+            .store(&tagged_manifest(b"atoms", &["science/physics", "sciences"]))
+            .unwrap();
+
+        let count = |tag: &str| tags.get(tag).unwrap().unwrap().content_count;
+        assert_eq!(count("science"), 2);
+        assert_eq!(count("science/biology"), 1);
+        assert_eq!(count("science/physics"), 1);
+        // A shared prefix isn't an ancestor
+        assert_eq!(count("sciences"), 1);
+    }
+
+    #[test]
+    fn test_autocomplete() {
+        let (tags, manifests) = setup();
+        for tag in ["science/biology", "science/physics", "biochemistry", "art"] {
+            tags.register(tag).unwrap();
+        }
+        manifests
+            .store(&tagged_manifest(b"enzymes", &["biochemistry"]))
+            .unwrap();
+        manifests
+            .store(&tagged_manifest(b"proteins", &["biochemistry"]))
+            .unwrap();
+
+        // Matches the start of any segment, most used first
+        let suggested: Vec<String> = tags
+            .autocomplete("Bio", 10)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.tag)
+            .collect();
+        assert_eq!(suggested, vec!["biochemistry", "science/biology"]);
+
+        let suggested: Vec<String> = tags
+            .autocomplete("science/p", 10)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.tag)
+            .collect();
+        assert_eq!(suggested, vec!["science/physics"]);
+
+        assert_eq!(tags.autocomplete("", 2).unwrap().len(), 2);
+        assert!(tags.autocomplete("zoology", 10).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,498 @@
+//! Tenant storage.
+//!
+//! Tenants are end-users served from this node's identity. Each has an API
+//! key, stored only as a hash, and an optional budget. Queries paid for on
+//! a tenant's behalf are tagged with it by payment ID, and content we
+//! publish for a tenant is attributed to it, so earnings can be split out.
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{Hash, Timestamp};
+use nodalync_types::Amount;
+
+use crate::error::{Result, StoreError};
+use crate::traits::TenantStore;
+use crate::types::{AccessKind, Tenant, TenantPurchase};
+
+/// Columns selected for a [`Tenant`], in `row_to_tenant` order.
+const TENANT_COLUMNS: &str = "id, name, budget, spent, created_at";
+
+/// Columns selected for a [`TenantPurchase`], in `row_to_purchase` order.
+const PURCHASE_COLUMNS: &str = "payment_id, tenant, content_hash, amount, purchased_at";
+
+/// SQLite-based tenant store.
+pub struct SqliteTenantStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteTenantStore {
+    /// Create a new tenant store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+}
+
+fn row_to_tenant(row: &Row<'_>) -> rusqlite::Result<Tenant> {
+    Ok(Tenant {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        budget: row.get::<_, Option<i64>>(2)?.map(|b| b as Amount),
+        spent: row.get::<_, i64>(3)? as Amount,
+        created_at: row.get::<_, i64>(4)? as Timestamp,
+    })
+}
+
+fn row_to_purchase(row: &Row<'_>) -> rusqlite::Result<TenantPurchase> {
+    let payment_id: Vec<u8> = row.get(0)?;
+    let content_hash: Vec<u8> = row.get(2)?;
+    Ok(TenantPurchase {
+        payment_id: bytes_to_hash(&payment_id),
+        tenant: row.get(1)?,
+        content_hash: bytes_to_hash(&content_hash),
+        amount: row.get::<_, i64>(3)? as Amount,
+        purchased_at: row.get::<_, i64>(4)? as Timestamp,
+    })
+}
+
+impl TenantStore for SqliteTenantStore {
+    fn create(&self, tenant: &Tenant, key_hash: &Hash) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        conn.execute(
+            "INSERT INTO tenants (id, name, key_hash, budget, spent, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                tenant.id,
+                tenant.name,
+                key_hash.0.to_vec(),
+                tenant.budget.map(|b| b as i64),
+                tenant.spent as i64,
+                tenant.created_at as i64
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn get(&self, id: &str) -> Result<Option<Tenant>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let tenant = conn
+            .prepare_cached(&format!(
+                "SELECT {} FROM tenants WHERE id = ?1",
+                TENANT_COLUMNS
+            ))?
+            .query_row([id], row_to_tenant)
+            .optional()?;
+
+        Ok(tenant)
+    }
+
+    fn by_key(&self, key_hash: &Hash) -> Result<Option<Tenant>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let tenant = conn
+            .prepare_cached(&format!(
+                "SELECT {} FROM tenants WHERE key_hash = ?1",
+                TENANT_COLUMNS
+            ))?
+            .query_row([key_hash.0.to_vec()], row_to_tenant)
+            .optional()?;
+
+        Ok(tenant)
+    }
+
+    fn list(&self) -> Result<Vec<Tenant>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM tenants ORDER BY created_at ASC, id ASC",
+            TENANT_COLUMNS
+        ))?;
+        let tenants = stmt
+            .query_map([], row_to_tenant)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(tenants)
+    }
+
+    fn set_budget(&self, id: &str, budget: Option<Amount>) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let updated = conn.execute(
+            "UPDATE tenants SET budget = ?2 WHERE id = ?1",
+            params![id, budget.map(|b| b as i64)],
+        )?;
+        if updated == 0 {
+            return Err(StoreError::TenantNotFound(id.to_string()));
+        }
+
+        Ok(())
+    }
+
+    fn set_key(&self, id: &str, key_hash: &Hash) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let updated = conn.execute(
+            "UPDATE tenants SET key_hash = ?2 WHERE id = ?1",
+            params![id, key_hash.0.to_vec()],
+        )?;
+        if updated == 0 {
+            return Err(StoreError::TenantNotFound(id.to_string()));
+        }
+
+        Ok(())
+    }
+
+    fn remove(&self, id: &str) -> Result<bool> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+        let tx = conn.transaction()?;
+
+        let deleted = tx.execute("DELETE FROM tenants WHERE id = ?1", [id])?;
+        tx.execute("DELETE FROM tenant_content WHERE tenant = ?1", [id])?;
+
+        tx.commit()?;
+        Ok(deleted > 0)
+    }
+
+    fn record_purchase(&self, purchase: &TenantPurchase) -> Result<bool> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+        let tx = conn.transaction()?;
+
+        let exists: i64 = tx.query_row(
+            "SELECT COUNT(*) FROM tenants WHERE id = ?1",
+            [&purchase.tenant],
+            |row| row.get(0),
+        )?;
+        if exists == 0 {
+            return Err(StoreError::TenantNotFound(purchase.tenant.clone()));
+        }
+        let inserted = tx.execute(
+            "INSERT INTO tenant_purchases
+             (payment_id, tenant, content_hash, amount, purchased_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(payment_id) DO NOTHING",
+            params![
+                purchase.payment_id.0.to_vec(),
+                purchase.tenant,
+                purchase.content_hash.0.to_vec(),
+                purchase.amount as i64,
+                purchase.purchased_at as i64
+            ],
+        )?;
+        if inserted > 0 {
+            tx.execute(
+                "UPDATE tenants SET spent = spent + ?2 WHERE id = ?1",
+                params![purchase.tenant, purchase.amount as i64],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(inserted > 0)
+    }
+
+    fn purchases(&self, tenant: Option<&str>, limit: u32) -> Result<Vec<TenantPurchase>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM tenant_purchases
+             WHERE ?1 IS NULL OR tenant = ?1
+             ORDER BY purchased_at DESC, rowid DESC
+             LIMIT ?2",
+            PURCHASE_COLUMNS
+        ))?;
+        let purchases = stmt
+            .query_map(params![tenant, limit as i64], row_to_purchase)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(purchases)
+    }
+
+    fn purchase_tenant(&self, payment_id: &Hash) -> Result<Option<String>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let tenant = conn
+            .prepare_cached("SELECT tenant FROM tenant_purchases WHERE payment_id = ?1")?
+            .query_row([payment_id.0.to_vec()], |row| row.get(0))
+            .optional()?;
+
+        Ok(tenant)
+    }
+
+    fn assign_content(&self, content_hash: &Hash, tenant: Option<&str>) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        match tenant {
+            Some(tenant) => conn.execute(
+                "INSERT INTO tenant_content (content_hash, tenant) VALUES (?1, ?2)
+                 ON CONFLICT(content_hash) DO UPDATE SET tenant = excluded.tenant",
+                params![content_hash.0.to_vec(), tenant],
+            )?,
+            None => conn.execute(
+                "DELETE FROM tenant_content WHERE content_hash = ?1",
+                [content_hash.0.to_vec()],
+            )?,
+        };
+
+        Ok(())
+    }
+
+    fn content_tenant(&self, content_hash: &Hash) -> Result<Option<String>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let tenant = conn
+            .prepare_cached("SELECT tenant FROM tenant_content WHERE content_hash = ?1")?
+            .query_row([content_hash.0.to_vec()], |row| row.get(0))
+            .optional()?;
+
+        Ok(tenant)
+    }
+
+    fn content(&self, tenant: &str) -> Result<Vec<Hash>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare_cached(
+            "SELECT content_hash FROM tenant_content WHERE tenant = ?1 ORDER BY rowid ASC",
+        )?;
+        let hashes = stmt
+            .query_map([tenant], |row| row.get::<_, Vec<u8>>(0))?
+            .map(|bytes| bytes.map(|bytes| bytes_to_hash(&bytes)))
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(hashes)
+    }
+
+    fn earnings(&self, tenant: &str) -> Result<Amount> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let total: i64 = conn
+            .prepare_cached(
+                "SELECT COALESCE(SUM(a.amount), 0)
+                 FROM content_access a
+                 JOIN tenant_content t ON t.content_hash = a.content_hash
+                 WHERE t.tenant = ?1 AND a.kind = ?2",
+            )?
+            .query_row(params![tenant, AccessKind::Query.as_i64()], |row| {
+                row.get(0)
+            })?;
+
+        Ok(total as Amount)
+    }
+}
+
+/// Convert bytes to Hash.
+fn bytes_to_hash(bytes: &[u8]) -> Hash {
+    let mut arr = [0u8; 32];
+    if bytes.len() >= 32 {
+        arr.copy_from_slice(&bytes[..32]);
+    }
+    Hash(arr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access::SqliteAccessLog;
+    use crate::schema::initialize_schema;
+    use crate::traits::AccessLogStore;
+    use crate::types::{AccessRecord, AccessRequester};
+    use nodalync_crypto::{content_hash, PeerId};
+
+    fn setup() -> (SqliteTenantStore, SqliteAccessLog) {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        let conn = Arc::new(Mutex::new(conn));
+        (
+            SqliteTenantStore::new(Arc::clone(&conn)),
+            SqliteAccessLog::new(conn),
+        )
+    }
+
+    fn tenant(id: &str, budget: Option<Amount>, created_at: Timestamp) -> Tenant {
+        Tenant {
+            id: id.to_string(),
+            name: format!("{id} inc"),
+            budget,
+            spent: 0,
+            created_at,
+        }
+    }
+
+    #[test]
+    fn test_create_lookup_and_remove() {
+        let (store, _) = setup();
+        store
+            .create(&tenant("acme", Some(500), 1_000), &content_hash(b"key-a"))
+            .unwrap();
+        store
+            .create(&tenant("globex", None, 2_000), &content_hash(b"key-g"))
+            .unwrap();
+
+        // Keys are unique
+        assert!(store
+            .create(&tenant("initech", None, 3_000), &content_hash(b"key-a"))
+            .is_err());
+
+        assert_eq!(
+            store.get("acme").unwrap(),
+            Some(tenant("acme", Some(500), 1_000))
+        );
+        let found = store.by_key(&content_hash(b"key-g")).unwrap().unwrap();
+        assert_eq!(found.id, "globex");
+        assert!(store.by_key(&content_hash(b"nope")).unwrap().is_none());
+
+        store.set_key("globex", &content_hash(b"key-g2")).unwrap();
+        assert!(store.by_key(&content_hash(b"key-g")).unwrap().is_none());
+        assert!(store.by_key(&content_hash(b"key-g2")).unwrap().is_some());
+
+        store.set_budget("globex", Some(10)).unwrap();
+        assert_eq!(store.get("globex").unwrap().unwrap().budget, Some(10));
+        assert!(matches!(
+            store.set_budget("nobody", None),
+            Err(StoreError::TenantNotFound(_))
+        ));
+
+        let ids: Vec<String> = store.list().unwrap().into_iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec!["acme", "globex"]);
+
+        assert!(store.remove("acme").unwrap());
+        assert!(!store.remove("acme").unwrap());
+        assert!(store.get("acme").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_purchases_update_spending() {
+        let (store, _) = setup();
+        store
+            .create(&tenant("acme", Some(500), 1_000), &content_hash(b"key-a"))
+            .unwrap();
+
+        let purchase = |i: u8, amount: Amount| TenantPurchase {
+            payment_id: content_hash(&[i]),
+            tenant: "acme".to_string(),
+            content_hash: content_hash(b"doc"),
+            amount,
+            purchased_at: 2_000 + i as Timestamp,
+        };
+        assert!(store.record_purchase(&purchase(0, 100)).unwrap());
+        assert!(store.record_purchase(&purchase(1, 150)).unwrap());
+        // A receipt is only charged once
+        assert!(!store.record_purchase(&purchase(1, 150)).unwrap());
+
+        let acme = store.get("acme").unwrap().unwrap();
+        assert_eq!(acme.spent, 250);
+        assert_eq!(acme.remaining_budget(), Some(250));
+        assert!(acme.can_afford(250));
+        assert!(!acme.can_afford(251));
+
+        let purchases = store.purchases(Some("acme"), 10).unwrap();
+        assert_eq!(purchases.len(), 2);
+        assert_eq!(purchases[0].amount, 150);
+        assert!(store.purchases(Some("globex"), 10).unwrap().is_empty());
+        assert_eq!(
+            store
+                .purchase_tenant(&content_hash(&[0]))
+                .unwrap()
+                .as_deref(),
+            Some("acme")
+        );
+        assert!(store
+            .purchase_tenant(&content_hash(&[9]))
+            .unwrap()
+            .is_none());
+
+        // Unknown tenants are rejected without recording anything
+        let orphan = TenantPurchase {
+            payment_id: content_hash(&[7]),
+            tenant: "nobody".to_string(),
+            content_hash: content_hash(b"doc"),
+            amount: 1,
+            purchased_at: 3_000,
+        };
+        assert!(matches!(
+            store.record_purchase(&orphan),
+            Err(StoreError::TenantNotFound(_))
+        ));
+        assert_eq!(store.purchases(None, 10).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_content_attribution_and_earnings() {
+        let (store, access) = setup();
+        store
+            .create(&tenant("acme", None, 1_000), &content_hash(b"key-a"))
+            .unwrap();
+        let ours = content_hash(b"acme report");
+        let other = content_hash(b"node report");
+        store.assign_content(&ours, Some("acme")).unwrap();
+        assert_eq!(
+            store.content_tenant(&ours).unwrap().as_deref(),
+            Some("acme")
+        );
+        assert_eq!(store.content("acme").unwrap(), vec![ours]);
+
+        let requester = AccessRequester::Peer(PeerId([3u8; 20]));
+        for (hash, kind, amount) in [
+            (ours, AccessKind::Query, 40),
+            (ours, AccessKind::Preview, 0),
+            (other, AccessKind::Query, 99),
+        ] {
+            access
+                .record(&AccessRecord::new(hash, requester, kind, amount, 2_000))
+                .unwrap();
+        }
+        assert_eq!(store.earnings("acme").unwrap(), 40);
+
+        store.assign_content(&ours, None).unwrap();
+        assert_eq!(store.content_tenant(&ours).unwrap(), None);
+        assert_eq!(store.earnings("acme").unwrap(), 0);
+
+        // Removing a tenant drops its attributions
+        store.assign_content(&ours, Some("acme")).unwrap();
+        store.remove("acme").unwrap();
+        assert_eq!(store.content_tenant(&ours).unwrap(), None);
+    }
+}
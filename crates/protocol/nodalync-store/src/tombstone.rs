@@ -0,0 +1,132 @@
+//! Tombstone storage.
+//!
+//! Tombstones record content its owner has withdrawn, both our own and
+//! ones received from other owners. A tombstoned hash stays withdrawn: it
+//! is not served, cached or re-listed, so tombstones are never removed.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{Hash, Timestamp};
+use nodalync_types::Tombstone;
+
+use crate::error::{Result, StoreError};
+use crate::traits::TombstoneStore;
+
+/// SQLite-based tombstone store.
+pub struct SqliteTombstoneStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteTombstoneStore {
+    /// Create a new tombstone store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+}
+
+impl TombstoneStore for SqliteTombstoneStore {
+    fn store(&self, tombstone: &Tombstone, received_at: Timestamp) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let data = serde_json::to_string(tombstone)?;
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO tombstones (content_hash, owner, data, received_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                tombstone.content_hash.0.to_vec(),
+                tombstone.owner.0.to_vec(),
+                data,
+                received_at as i64,
+            ],
+        )?;
+
+        Ok(inserted > 0)
+    }
+
+    fn get(&self, content_hash: &Hash) -> Result<Option<Tombstone>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let data: Option<String> = conn
+            .prepare_cached("SELECT data FROM tombstones WHERE content_hash = ?1")?
+            .query_row([content_hash.0.to_vec()], |row| row.get(0))
+            .optional()?;
+
+        Ok(data.map(|data| serde_json::from_str(&data)).transpose()?)
+    }
+
+    fn list(&self) -> Result<Vec<Tombstone>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare("SELECT data FROM tombstones ORDER BY received_at DESC")?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        rows.iter()
+            .map(|data| Ok(serde_json::from_str(data)?))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+
+    fn setup_store() -> SqliteTombstoneStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteTombstoneStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    fn test_tombstone(content: &[u8], created_at: Timestamp) -> Tombstone {
+        let (_, public_key) = generate_identity();
+        Tombstone::new(
+            content_hash(content),
+            peer_id_from_public_key(&public_key),
+            public_key,
+            created_at,
+        )
+    }
+
+    #[test]
+    fn test_store_and_get() {
+        let store = setup_store();
+        let tombstone = test_tombstone(b"withdrawn", 100);
+
+        assert!(store.get(&tombstone.content_hash).unwrap().is_none());
+        assert!(store.store(&tombstone, 100).unwrap());
+        assert_eq!(
+            store.get(&tombstone.content_hash).unwrap(),
+            Some(tombstone.clone())
+        );
+
+        // The first tombstone for a hash is kept
+        let mut later = tombstone.clone();
+        later.created_at = 200;
+        assert!(!store.store(&later, 200).unwrap());
+        assert_eq!(store.get(&tombstone.content_hash).unwrap(), Some(tombstone));
+    }
+
+    #[test]
+    fn test_list_newest_first() {
+        let store = setup_store();
+        let first = test_tombstone(b"first", 100);
+        let second = test_tombstone(b"second", 200);
+        store.store(&first, 100).unwrap();
+        store.store(&second, 200).unwrap();
+
+        assert_eq!(store.list().unwrap(), vec![second, first]);
+    }
+}
@@ -4,11 +4,23 @@
 //! Implementations may vary (e.g., in-memory vs SQLite) but must satisfy
 //! these interfaces.
 
-use nodalync_crypto::{Hash, PeerId, Timestamp};
-use nodalync_types::{Amount, Channel, Manifest, Payment, ProvenanceEntry};
+use nodalync_crypto::{ContentKey, Hash, PeerId, Timestamp};
+use nodalync_types::{
+    Amount, CanonicalVersion, Channel, ContentReport, FraudProof, Group, Manifest, Payment,
+    ProvenanceEntry, ReplicaDelegation, RevocationCertificate, Tombstone,
+};
+use nodalync_wire::EncryptedSyncBundle;
 
 use crate::error::Result;
-use crate::types::{CachedContent, ManifestFilter, PeerInfo, QueuedDistribution};
+use crate::types::{
+    AccessRecord, AccessRequester, AvailabilityCheck, AvailabilityRecord, CachedContent,
+    DigestPeriod, DisputeRecord, Entitlement, InvoiceDirection, InvoiceRecord, InvoiceStatus,
+    LatencyStats, LedgerAccount, LedgerEntry, LedgerTransaction, LibraryEntry, ManifestFilter,
+    ModerationEntry, ModerationStatus, PeerInfo, PopularityKind, PopularityRecord, QueryReceipt,
+    QueuedDistribution, QuotaUsage, SettlementFailure, StandingOrder, StandingOrderPurchase,
+    StoredGroup, TagInfo, Tenant, TenantPurchase, UsageRecord, WebhookDelivery,
+    WebhookDeliveryStatus,
+};
 
 // =============================================================================
 // Content Storage
@@ -22,13 +34,13 @@ pub trait ContentStore {
     ///
     /// Computes the content hash and stores the content.
     /// If content with the same hash already exists, this is a no-op.
-    fn store(&mut self, content: &[u8]) -> Result<Hash>;
+    fn store(&self, content: &[u8]) -> Result<Hash>;
 
     /// Store content with a known hash.
     ///
     /// Verifies that the content matches the provided hash before storing.
     /// Returns an error if the hash doesn't match.
-    fn store_verified(&mut self, hash: &Hash, content: &[u8]) -> Result<()>;
+    fn store_verified(&self, hash: &Hash, content: &[u8]) -> Result<()>;
 
     /// Load content by hash.
     ///
@@ -41,12 +53,45 @@ pub trait ContentStore {
     /// Delete content by hash.
     ///
     /// Returns Ok(()) even if the content doesn't exist.
-    fn delete(&mut self, hash: &Hash) -> Result<()>;
+    fn delete(&self, hash: &Hash) -> Result<()>;
 
     /// Get content size without loading the full content.
     ///
     /// Returns `None` if the content doesn't exist.
     fn size(&self, hash: &Hash) -> Result<Option<u64>>;
+
+    /// Move content out of the store into quarantine.
+    ///
+    /// Quarantined content is no longer loaded or served, but is kept for
+    /// inspection. Returns Ok(()) even if the content doesn't exist.
+    fn quarantine(&self, hash: &Hash) -> Result<()>;
+
+    /// List quarantined content hashes.
+    fn quarantined(&self) -> Result<Vec<Hash>>;
+
+    /// Delete a quarantined copy (e.g. once a good copy is restored).
+    ///
+    /// Returns Ok(()) even if there is no quarantined copy.
+    fn release_quarantined(&self, hash: &Hash) -> Result<()>;
+}
+
+/// Trait for storing deltas between content versions.
+///
+/// A delta reconstructs `target` from `base` (see [`crate::delta`]).
+/// Deltas are kept alongside the full blobs, never instead of them.
+pub trait DeltaStore {
+    /// Store the delta that turns `base` into `target`.
+    fn store_delta(&self, base: &Hash, target: &Hash, delta: &[u8]) -> Result<()>;
+
+    /// Load the delta that turns `base` into `target`.
+    ///
+    /// Returns `None` if no such delta is stored.
+    fn load_delta(&self, base: &Hash, target: &Hash) -> Result<Option<Vec<u8>>>;
+
+    /// Delete every delta to or from a content hash.
+    ///
+    /// Returns Ok(()) even if there are none.
+    fn delete_deltas(&self, hash: &Hash) -> Result<()>;
 }
 
 // =============================================================================
@@ -60,7 +105,13 @@ pub trait ManifestStore {
     /// Store a manifest.
     ///
     /// If a manifest with the same hash exists, this is a no-op.
-    fn store(&mut self, manifest: &Manifest) -> Result<()>;
+    fn store(&self, manifest: &Manifest) -> Result<()>;
+
+    /// Store many manifests in one transaction.
+    ///
+    /// Manifests whose hash already exists are skipped, as with
+    /// [`store`](Self::store). Returns the number newly stored.
+    fn store_batch(&self, manifests: &[Manifest]) -> Result<usize>;
 
     /// Load a manifest by content hash.
     ///
@@ -70,12 +121,12 @@ pub trait ManifestStore {
     /// Update an existing manifest.
     ///
     /// Returns an error if the manifest doesn't exist.
-    fn update(&mut self, manifest: &Manifest) -> Result<()>;
+    fn update(&self, manifest: &Manifest) -> Result<()>;
 
     /// Delete a manifest by hash.
     ///
     /// Returns Ok(()) even if the manifest doesn't exist.
-    fn delete(&mut self, hash: &Hash) -> Result<()>;
+    fn delete(&self, hash: &Hash) -> Result<()>;
 
     /// List manifests matching filter criteria.
     fn list(&self, filter: ManifestFilter) -> Result<Vec<Manifest>>;
@@ -85,6 +136,16 @@ pub trait ManifestStore {
     /// Returns all manifests that share the same version root,
     /// ordered by version number.
     fn get_versions(&self, version_root: &Hash) -> Result<Vec<Manifest>>;
+
+    /// Store an owner's canonical version pointer for its version root.
+    ///
+    /// Replaces an earlier pointer for the root; an older or equally old
+    /// pointer is ignored. Returns whether it was stored. Callers must
+    /// validate the pointer first.
+    fn set_canonical(&self, pointer: &CanonicalVersion) -> Result<bool>;
+
+    /// Get the canonical version pointer for a version root.
+    fn get_canonical(&self, version_root: &Hash) -> Result<Option<CanonicalVersion>>;
 }
 
 // =============================================================================
@@ -100,7 +161,7 @@ pub trait ProvenanceGraph {
     ///
     /// For L0 content, `derived_from` should be empty.
     /// For L3 content, `derived_from` contains the hashes of source content.
-    fn add(&mut self, hash: &Hash, derived_from: &[Hash]) -> Result<()>;
+    fn add(&self, hash: &Hash, derived_from: &[Hash]) -> Result<()>;
 
     /// Get all root L0+L1 sources for content (flattened).
     ///
@@ -120,7 +181,7 @@ pub trait ProvenanceGraph {
     /// Store a provenance entry in the root cache.
     ///
     /// This is used to cache flattened root entries for performance.
-    fn cache_root(&mut self, content_hash: &Hash, entry: &ProvenanceEntry) -> Result<()>;
+    fn cache_root(&self, content_hash: &Hash, entry: &ProvenanceEntry) -> Result<()>;
 }
 
 // =============================================================================
@@ -134,7 +195,7 @@ pub trait ChannelStore {
     /// Create a new channel with a peer.
     ///
     /// Returns an error if a channel already exists with this peer.
-    fn create(&mut self, peer: &PeerId, channel: Channel) -> Result<()>;
+    fn create(&self, peer: &PeerId, channel: Channel) -> Result<()>;
 
     /// Get channel state for a peer.
     ///
@@ -144,7 +205,7 @@ pub trait ChannelStore {
     /// Update channel state.
     ///
     /// Returns an error if no channel exists with this peer.
-    fn update(&mut self, peer: &PeerId, channel: &Channel) -> Result<()>;
+    fn update(&self, peer: &PeerId, channel: &Channel) -> Result<()>;
 
     /// List all open channels.
     ///
@@ -154,12 +215,12 @@ pub trait ChannelStore {
     /// Clear all channels.
     ///
     /// Used for recovery when channel state becomes inconsistent.
-    fn clear_all(&mut self) -> Result<()>;
+    fn clear_all(&self) -> Result<()>;
 
     /// Add a payment to a channel.
     ///
     /// The payment is stored as pending until settlement.
-    fn add_payment(&mut self, peer: &PeerId, payment: Payment) -> Result<()>;
+    fn add_payment(&self, peer: &PeerId, payment: Payment) -> Result<()>;
 
     /// Get pending payments for a channel.
     fn get_pending_payments(&self, peer: &PeerId) -> Result<Vec<Payment>>;
@@ -167,7 +228,7 @@ pub trait ChannelStore {
     /// Clear pending payments by ID.
     ///
     /// Called after payments have been settled.
-    fn clear_payments(&mut self, peer: &PeerId, payment_ids: &[Hash]) -> Result<()>;
+    fn clear_payments(&self, peer: &PeerId, payment_ids: &[Hash]) -> Result<()>;
 }
 
 // =============================================================================
@@ -181,7 +242,7 @@ pub trait PeerStore {
     /// Insert or update peer information.
     ///
     /// If the peer exists, updates the record. Otherwise, inserts a new one.
-    fn upsert(&mut self, peer: &PeerInfo) -> Result<()>;
+    fn upsert(&self, peer: &PeerInfo) -> Result<()>;
 
     /// Get peer information by ID.
     ///
@@ -194,18 +255,29 @@ pub trait PeerStore {
     /// Update last seen timestamp for a peer.
     ///
     /// Returns an error if the peer is not known.
-    fn update_last_seen(&mut self, peer_id: &PeerId, timestamp: Timestamp) -> Result<()>;
+    fn update_last_seen(&self, peer_id: &PeerId, timestamp: Timestamp) -> Result<()>;
 
     /// Adjust reputation for a peer.
     ///
     /// Delta can be positive or negative.
     /// Returns an error if the peer is not known.
-    fn update_reputation(&mut self, peer_id: &PeerId, delta: i64) -> Result<()>;
+    fn update_reputation(&self, peer_id: &PeerId, delta: i64) -> Result<()>;
+
+    /// Record a latency probe of a peer: its round trip in milliseconds,
+    /// or `None` if it went unanswered. Returns the updated statistics.
+    ///
+    /// Returns an error if the peer is not known.
+    fn record_latency(
+        &self,
+        peer_id: &PeerId,
+        rtt_ms: Option<u32>,
+        timestamp: Timestamp,
+    ) -> Result<LatencyStats>;
 
     /// Delete a peer from the store.
     ///
     /// Returns Ok(()) even if the peer doesn't exist.
-    fn delete(&mut self, peer_id: &PeerId) -> Result<()>;
+    fn delete(&self, peer_id: &PeerId) -> Result<()>;
 }
 
 // =============================================================================
@@ -219,7 +291,7 @@ pub trait CacheStore {
     /// Cache content from a query.
     ///
     /// Stores both the content and metadata about the query.
-    fn cache(&mut self, entry: CachedContent) -> Result<()>;
+    fn cache(&self, entry: CachedContent) -> Result<()>;
 
     /// Get cached content.
     ///
@@ -233,10 +305,13 @@ pub trait CacheStore {
     ///
     /// Removes entries until total cache size is below `max_size_bytes`.
     /// Returns the number of bytes freed.
-    fn evict(&mut self, max_size_bytes: u64) -> Result<u64>;
+    fn evict(&self, max_size_bytes: u64) -> Result<u64>;
+
+    /// Remove one cached entry. Returns `false` if it wasn't cached.
+    fn remove(&self, hash: &Hash) -> Result<bool>;
 
     /// Clear all cached content.
-    fn clear(&mut self) -> Result<()>;
+    fn clear(&self) -> Result<()>;
 
     /// Get total cache size in bytes.
     fn total_size(&self) -> Result<u64>;
@@ -251,7 +326,7 @@ pub trait CacheStore {
 /// Stores pending distributions until they are batch-settled on-chain.
 pub trait SettlementQueueStore {
     /// Add a distribution to the queue.
-    fn enqueue(&mut self, distribution: QueuedDistribution) -> Result<()>;
+    fn enqueue(&self, distribution: QueuedDistribution) -> Result<()>;
 
     /// Get all pending distributions.
     fn get_pending(&self) -> Result<Vec<QueuedDistribution>>;
@@ -265,7 +340,7 @@ pub trait SettlementQueueStore {
     /// Mark distributions as settled.
     ///
     /// Associates the distributions with a batch ID.
-    fn mark_settled(&mut self, payment_ids: &[Hash], batch_id: &Hash) -> Result<()>;
+    fn mark_settled(&self, payment_ids: &[Hash], batch_id: &Hash) -> Result<()>;
 
     /// Get the last settlement timestamp.
     ///
@@ -273,5 +348,683 @@ pub trait SettlementQueueStore {
     fn get_last_settlement_time(&self) -> Result<Option<Timestamp>>;
 
     /// Set the last settlement timestamp.
-    fn set_last_settlement_time(&mut self, timestamp: Timestamp) -> Result<()>;
+    fn set_last_settlement_time(&self, timestamp: Timestamp) -> Result<()>;
+}
+
+// =============================================================================
+// Access Log Storage
+// =============================================================================
+
+/// Trait for the per-content access log.
+///
+/// Records previews and paid queries served for local content, and usage
+/// reports sent back by consumers, so publishers can see how their content
+/// is used.
+pub trait AccessLogStore {
+    /// Record a served access.
+    fn record(&self, record: &AccessRecord) -> Result<()>;
+
+    /// Get all accesses to a content hash, oldest first.
+    fn for_content(&self, hash: &Hash) -> Result<Vec<AccessRecord>>;
+
+    /// Get all accesses served in `[from, to)`, oldest first.
+    fn between(&self, from: Timestamp, to: Timestamp) -> Result<Vec<AccessRecord>>;
+
+    /// Requesters whose first recorded access falls in `[from, to)`.
+    ///
+    /// Only what is still in the log counts, so a pruned consumer shows up
+    /// as new again.
+    fn first_seen_between(&self, from: Timestamp, to: Timestamp) -> Result<Vec<AccessRequester>>;
+
+    /// Record a usage report received from a consumer.
+    fn record_usage(&self, record: &UsageRecord) -> Result<()>;
+
+    /// Get all usage reports for a content hash, oldest first.
+    fn usage_for_content(&self, hash: &Hash) -> Result<Vec<UsageRecord>>;
+
+    /// Delete accesses and usage reports older than the given timestamp.
+    ///
+    /// Returns the number of records deleted.
+    fn prune(&self, older_than: Timestamp) -> Result<u64>;
+}
+
+// =============================================================================
+// Popularity Counters
+// =============================================================================
+
+/// Trait for per-hash request counters and decaying popularity scores.
+pub trait PopularityStore {
+    /// Count a request for a content hash at `timestamp`.
+    ///
+    /// The stored score is decayed to `timestamp` with `half_life_ms`
+    /// before the request's weight is added.
+    fn record(
+        &self,
+        hash: &Hash,
+        kind: PopularityKind,
+        half_life_ms: u64,
+        timestamp: Timestamp,
+    ) -> Result<()>;
+
+    /// Get the counters for a content hash.
+    fn get(&self, hash: &Hash) -> Result<Option<PopularityRecord>>;
+
+    /// The `limit` most popular hashes at `now`, most popular first, with
+    /// their scores decayed to `now`.
+    fn top(&self, limit: usize, half_life_ms: u64, now: Timestamp)
+        -> Result<Vec<PopularityRecord>>;
+
+    /// Delete counters last updated before the given timestamp.
+    ///
+    /// Returns the number of records deleted.
+    fn prune(&self, older_than: Timestamp) -> Result<u64>;
+}
+
+// =============================================================================
+// Metadata Schema Cache
+// =============================================================================
+
+/// Trait for caching metadata schemas by URI.
+///
+/// Schemas are stored as raw JSON; parsing and validation are left to
+/// `nodalync-valid`.
+pub trait MetadataSchemaStore {
+    /// Store a schema, replacing any schema cached under the same URI.
+    fn put(&self, uri: &str, schema: &str) -> Result<()>;
+
+    /// Get a cached schema by URI.
+    fn get(&self, uri: &str) -> Result<Option<String>>;
+
+    /// List cached schema URIs, sorted.
+    fn list(&self) -> Result<Vec<String>>;
+
+    /// Remove a cached schema.
+    ///
+    /// Returns Ok(()) even if no schema is cached under the URI.
+    fn remove(&self, uri: &str) -> Result<()>;
+}
+
+// =============================================================================
+// Tag Registry
+// =============================================================================
+
+/// Trait for the registry of hierarchical tags.
+///
+/// Tags are normalized with `nodalync_types::normalize_tag` before they are
+/// stored or looked up. Content counts are computed from the local manifests
+/// and include content filed under descendant tags.
+pub trait TagStore {
+    /// Register a tag and its ancestors.
+    ///
+    /// Returns the normalized tag, or `None` if the tag is empty after
+    /// normalization. Registering a known tag is a no-op.
+    fn register(&self, tag: &str) -> Result<Option<String>>;
+
+    /// Get a registered tag.
+    fn get(&self, tag: &str) -> Result<Option<TagInfo>>;
+
+    /// List registered tags, sorted.
+    fn list(&self) -> Result<Vec<TagInfo>>;
+
+    /// Suggest registered tags for a partially typed tag.
+    ///
+    /// A tag matches if it, or one of its segments, starts with the
+    /// normalized prefix. Results are ordered by content count (descending),
+    /// then tag.
+    fn autocomplete(&self, prefix: &str, limit: u32) -> Result<Vec<TagInfo>>;
+}
+
+// =============================================================================
+// Economic Ledger
+// =============================================================================
+
+/// Trait for the double-entry economic ledger.
+///
+/// Every economic event is posted as a balanced transaction; each posting
+/// is stored as an entry with the account's running balance.
+pub trait LedgerStore {
+    /// Post a transaction and return its ID.
+    ///
+    /// Fails with `StoreError::InvalidData` if the transaction has no
+    /// postings or its debits and credits don't balance.
+    fn post(&self, transaction: &LedgerTransaction) -> Result<u64>;
+
+    /// List entries oldest first, optionally for one account and from a
+    /// timestamp onwards.
+    fn entries(
+        &self,
+        account: Option<LedgerAccount>,
+        since: Option<Timestamp>,
+    ) -> Result<Vec<LedgerEntry>>;
+
+    /// Get the current balance of an account (0 if it has no entries).
+    fn balance(&self, account: LedgerAccount) -> Result<i64>;
+
+    /// Get the total debits and credits across all entries.
+    fn totals(&self) -> Result<(Amount, Amount)>;
+}
+
+// =============================================================================
+// Invoice Store
+// =============================================================================
+
+/// Storage for invoices issued and received by this node.
+pub trait InvoiceStore {
+    /// Store an invoice.
+    ///
+    /// Returns `false` if an invoice with the same hash is already stored,
+    /// in which case the stored record is left unchanged.
+    fn insert(&self, record: &InvoiceRecord) -> Result<bool>;
+
+    /// Get an invoice by hash.
+    fn get(&self, hash: &Hash) -> Result<Option<InvoiceRecord>>;
+
+    /// List invoices newest first, optionally filtered.
+    fn list(
+        &self,
+        direction: Option<InvoiceDirection>,
+        status: Option<InvoiceStatus>,
+    ) -> Result<Vec<InvoiceRecord>>;
+
+    /// Mark an unpaid invoice as paid.
+    ///
+    /// Returns `false` if the invoice doesn't exist or is already paid, so
+    /// an invoice can only be paid once.
+    fn mark_paid(
+        &self,
+        hash: &Hash,
+        payer: &PeerId,
+        payment_id: &Hash,
+        paid_at: Timestamp,
+    ) -> Result<bool>;
+}
+
+// =============================================================================
+// Group Store
+// =============================================================================
+
+/// Storage for group membership lists, owned or cached.
+pub trait GroupStore {
+    /// Store a group.
+    ///
+    /// Returns `false` if an older version than the stored one is given,
+    /// in which case the stored record is left unchanged. Storing the same
+    /// version again refreshes `fetched_at`.
+    fn store(&self, group: &Group, fetched_at: Timestamp) -> Result<bool>;
+
+    /// Get a group by ID.
+    fn get(&self, id: &Hash) -> Result<Option<StoredGroup>>;
+
+    /// List all groups, ordered by name.
+    fn list(&self) -> Result<Vec<StoredGroup>>;
+
+    /// Delete a group. Returns `false` if it wasn't stored.
+    fn delete(&self, id: &Hash) -> Result<bool>;
+}
+
+// =============================================================================
+// Content Key Storage
+// =============================================================================
+
+/// Storage for the keys restricted content is encrypted under.
+pub trait ContentKeyStore {
+    /// Get the key for a content hash, if one has been created.
+    fn get(&self, hash: &Hash) -> Result<Option<ContentKey>>;
+
+    /// Get the key for a content hash, creating and storing one if needed.
+    fn get_or_create(&self, hash: &Hash, created_at: Timestamp) -> Result<ContentKey>;
+
+    /// Delete the key for a content hash. Returns `false` if there was none.
+    fn delete(&self, hash: &Hash) -> Result<bool>;
+}
+
+// =============================================================================
+// Tombstone Storage
+// =============================================================================
+
+/// Storage for owner-signed tombstones of withdrawn content.
+pub trait TombstoneStore {
+    /// Store a tombstone.
+    ///
+    /// Returns `false` if the content already has one, in which case the
+    /// stored tombstone is kept.
+    fn store(&self, tombstone: &Tombstone, received_at: Timestamp) -> Result<bool>;
+
+    /// Get the tombstone for a content hash, if it has been withdrawn.
+    fn get(&self, content_hash: &Hash) -> Result<Option<Tombstone>>;
+
+    /// List all tombstones, newest first.
+    fn list(&self) -> Result<Vec<Tombstone>>;
+}
+
+// =============================================================================
+// Moderation Storage
+// =============================================================================
+
+/// Storage for content reports and per-content moderation status.
+pub trait ModerationStore {
+    /// Store a report.
+    ///
+    /// Returns `false` if the reporter already reported this content, in
+    /// which case the stored report is kept. Content reported for the
+    /// first time enters the review queue as
+    /// [`ModerationStatus::Pending`].
+    fn store_report(&self, report: &ContentReport, received_at: Timestamp) -> Result<bool>;
+
+    /// Get all reports for a content hash, oldest first.
+    fn reports_for(&self, content_hash: &Hash) -> Result<Vec<ContentReport>>;
+
+    /// Get the moderation status of a content hash, if it was ever reported
+    /// or moderated.
+    fn get_status(&self, content_hash: &Hash) -> Result<Option<ModerationStatus>>;
+
+    /// Set the moderation status of a content hash.
+    fn set_status(
+        &self,
+        content_hash: &Hash,
+        status: ModerationStatus,
+        updated_at: Timestamp,
+    ) -> Result<()>;
+
+    /// List moderated content, most recently updated first, optionally
+    /// filtered by status.
+    fn list(&self, status: Option<ModerationStatus>) -> Result<Vec<ModerationEntry>>;
+}
+
+// =============================================================================
+// Revocation Storage
+// =============================================================================
+
+/// Storage for revocation certificates of compromised identity keys.
+pub trait RevocationStore {
+    /// Store a revocation certificate.
+    ///
+    /// Returns `false` if the key was already revoked, in which case the
+    /// stored certificate is kept.
+    fn store(&self, certificate: &RevocationCertificate, received_at: Timestamp) -> Result<bool>;
+
+    /// Get the revocation certificate for a peer, if its key was revoked.
+    fn get(&self, peer_id: &PeerId) -> Result<Option<RevocationCertificate>>;
+
+    /// Check whether a peer's key has been revoked.
+    fn is_revoked(&self, peer_id: &PeerId) -> Result<bool>;
+
+    /// List all revocations, newest first.
+    fn list(&self) -> Result<Vec<RevocationCertificate>>;
+}
+
+// =============================================================================
+// Trial Read Storage
+// =============================================================================
+
+/// Storage for the free trial reads of paid content served to each peer.
+pub trait TrialStore {
+    /// Free reads of a content hash served to a peer.
+    fn reads(&self, content_hash: &Hash, peer: &PeerId) -> Result<u32>;
+
+    /// Record a free read of a content hash served to a peer.
+    ///
+    /// Returns the peer's free reads of the hash, including this one.
+    fn record_read(&self, content_hash: &Hash, peer: &PeerId, at: Timestamp) -> Result<u32>;
+
+    /// Number of peers that have had free reads of a content hash.
+    fn readers(&self, content_hash: &Hash) -> Result<u32>;
+
+    /// Forget the free reads of a content hash, giving every peer a fresh
+    /// trial.
+    ///
+    /// Returns the number of peers whose reads were forgotten.
+    fn reset(&self, content_hash: &Hash) -> Result<usize>;
+}
+
+// =============================================================================
+// Quota Storage
+// =============================================================================
+
+/// Storage for the queries and bytes served to each peer per quota period
+/// (UTC day), for enforcing daily quotas.
+pub trait QuotaStore {
+    /// A peer's usage in a period: of one content hash, or node-wide with
+    /// `None`.
+    fn usage(&self, peer: &PeerId, content_hash: Option<&Hash>, period: u64) -> Result<QuotaUsage>;
+
+    /// Record a query served to a peer, adding it to both the content's
+    /// and the node-wide usage.
+    ///
+    /// The peer's usage from earlier periods is dropped.
+    fn record_use(&self, peer: &PeerId, content_hash: &Hash, period: u64, bytes: u64)
+        -> Result<()>;
+
+    /// Take back usage recorded for a query that wasn't served, from both
+    /// the content's and the node-wide usage.
+    fn release_use(
+        &self,
+        peer: &PeerId,
+        content_hash: &Hash,
+        period: u64,
+        bytes: u64,
+    ) -> Result<()>;
+
+    /// Every peer's usage in a period, of one content hash or node-wide,
+    /// most queries first.
+    fn peers(&self, content_hash: Option<&Hash>, period: u64) -> Result<Vec<(PeerId, QuotaUsage)>>;
+
+    /// Forget a peer's usage, giving it its full quotas again.
+    ///
+    /// Returns whether it had any.
+    fn reset(&self, peer: &PeerId) -> Result<bool>;
+}
+
+// =============================================================================
+// Library Storage
+// =============================================================================
+
+/// Storage for the library of content we bought.
+///
+/// Holds one entry per content hash, for its latest purchase.
+pub trait LibraryStore {
+    /// Record a purchase, replacing any earlier one of the same content.
+    fn record(&self, entry: &LibraryEntry) -> Result<()>;
+
+    /// Get the entry for a content hash.
+    fn get(&self, hash: &Hash) -> Result<Option<LibraryEntry>>;
+
+    /// List entries, most recently bought first.
+    fn list(&self) -> Result<Vec<LibraryEntry>>;
+
+    /// List entries whose title contains `text`, ignoring case, most
+    /// recently bought first.
+    fn search(&self, text: &str) -> Result<Vec<LibraryEntry>>;
+
+    /// Remove an entry. Returns `false` if there was none.
+    fn remove(&self, hash: &Hash) -> Result<bool>;
+}
+
+// =============================================================================
+// Entitlement Storage
+// =============================================================================
+
+/// Storage for the paid queries we served, which entitle their requesters
+/// to re-download the content.
+pub trait EntitlementStore {
+    /// Record a paid query, keyed by its receipt's payment ID.
+    fn grant(&self, entitlement: &Entitlement) -> Result<()>;
+
+    /// Get the entitlement for a receipt's payment ID.
+    fn get(&self, payment_id: &Hash) -> Result<Option<Entitlement>>;
+
+    /// Count a re-download at `at`. Returns `false` if there is no such
+    /// entitlement.
+    fn record_redemption(&self, payment_id: &Hash, at: Timestamp) -> Result<bool>;
+
+    /// Remove entitlements granted before `granted_before`. Returns how
+    /// many were removed.
+    fn prune(&self, granted_before: Timestamp) -> Result<usize>;
+}
+
+// =============================================================================
+// Dispute Storage
+// =============================================================================
+
+/// Storage for the channel disputes we are party to.
+pub trait DisputeStore {
+    /// Record a dispute, replacing any earlier record of the same channel.
+    fn track(&self, dispute: &DisputeRecord) -> Result<()>;
+
+    /// Get the dispute on a channel.
+    fn get(&self, channel_id: &Hash) -> Result<Option<DisputeRecord>>;
+
+    /// List disputes, soonest deadline first. Disputes that are over are
+    /// only included with `include_closed`.
+    fn list(&self, include_closed: bool) -> Result<Vec<DisputeRecord>>;
+}
+
+// =============================================================================
+// Fraud Proof Storage
+// =============================================================================
+
+/// Storage for fraud proofs against providers.
+pub trait FraudProofStore {
+    /// Store a fraud proof.
+    ///
+    /// Returns `false` if the proof was already stored.
+    fn store(&self, proof: &FraudProof, received_at: Timestamp) -> Result<bool>;
+
+    /// Get a fraud proof by its hash.
+    fn get(&self, hash: &Hash) -> Result<Option<FraudProof>>;
+
+    /// List fraud proofs, newest first, optionally only those against one
+    /// provider.
+    fn list(&self, provider: Option<&PeerId>) -> Result<Vec<FraudProof>>;
+}
+
+// =============================================================================
+// Sync Storage
+// =============================================================================
+
+/// Storage for moving state between devices of one identity.
+pub trait SyncStore {
+    /// Hold a bundle for its owner's other devices.
+    ///
+    /// Replaces the bundle held for the owner only if this one is newer.
+    /// Returns `false` if it was not stored.
+    fn put_bundle(&self, bundle: &EncryptedSyncBundle) -> Result<bool>;
+
+    /// Get the bundle held for an owner.
+    fn get_bundle(&self, owner: &PeerId) -> Result<Option<EncryptedSyncBundle>>;
+
+    /// Record a channel's nonce as of the last sync.
+    fn set_channel_base(&self, channel_id: &Hash, nonce: u64) -> Result<()>;
+
+    /// Get a channel's nonce as of the last sync.
+    fn get_channel_base(&self, channel_id: &Hash) -> Result<Option<u64>>;
+
+    /// Store a query receipt imported from another device.
+    ///
+    /// Returns `false` if a receipt with the same payment ID was already
+    /// stored.
+    fn store_receipt(&self, receipt: &QueryReceipt) -> Result<bool>;
+
+    /// List imported query receipts, newest first.
+    fn list_receipts(&self) -> Result<Vec<QueryReceipt>>;
+}
+
+// =============================================================================
+// Replica Storage
+// =============================================================================
+
+/// Storage for delegations from primaries whose catalogs we serve.
+pub trait ReplicaStore {
+    /// Store a delegation, replacing any held from the same primary.
+    fn set_delegation(&self, delegation: &ReplicaDelegation) -> Result<()>;
+
+    /// Get the delegation held from a primary.
+    fn get_delegation(&self, primary: &PeerId) -> Result<Option<ReplicaDelegation>>;
+
+    /// List the delegations held, soonest to expire first.
+    fn list_delegations(&self) -> Result<Vec<ReplicaDelegation>>;
+
+    /// Remove the delegation held from a primary.
+    ///
+    /// Returns `false` if none was held.
+    fn remove_delegation(&self, primary: &PeerId) -> Result<bool>;
+}
+
+// =============================================================================
+// Webhook Storage
+// =============================================================================
+
+/// Storage for webhook notifications and their delivery state.
+pub trait WebhookStore {
+    /// Queue a notification, due immediately. Returns its delivery ID.
+    fn enqueue(&self, url: &str, event: &str, payload: &str, created_at: Timestamp) -> Result<u64>;
+
+    /// Get a delivery by ID.
+    fn get(&self, id: u64) -> Result<Option<WebhookDelivery>>;
+
+    /// List pending deliveries due at `now`, oldest first.
+    fn due(&self, now: Timestamp, limit: u32) -> Result<Vec<WebhookDelivery>>;
+
+    /// Mark a delivery as accepted by its endpoint.
+    fn mark_delivered(&self, id: u64, at: Timestamp) -> Result<()>;
+
+    /// Record a failed attempt.
+    ///
+    /// The delivery is retried at `retry_at`, or marked failed when that
+    /// is `None`.
+    fn record_failure(
+        &self,
+        id: u64,
+        error: &str,
+        at: Timestamp,
+        retry_at: Option<Timestamp>,
+    ) -> Result<()>;
+
+    /// List deliveries newest first, optionally filtered by status.
+    fn list(
+        &self,
+        status: Option<WebhookDeliveryStatus>,
+        limit: u32,
+    ) -> Result<Vec<WebhookDelivery>>;
+
+    /// Delete delivered and failed deliveries finished before `before`.
+    ///
+    /// Returns the number deleted.
+    fn prune(&self, before: Timestamp) -> Result<usize>;
+}
+
+// =============================================================================
+// Standing Order Storage
+// =============================================================================
+
+/// Storage for standing orders and the content they have bought.
+pub trait StandingOrderStore {
+    /// Create a standing order. Returns its ID.
+    fn create(
+        &self,
+        tag: &str,
+        max_price: Amount,
+        min_reputation: Option<i64>,
+        budget: Option<Amount>,
+        created_at: Timestamp,
+    ) -> Result<u64>;
+
+    /// Get a standing order by ID.
+    fn get(&self, id: u64) -> Result<Option<StandingOrder>>;
+
+    /// List standing orders, oldest first.
+    fn list(&self) -> Result<Vec<StandingOrder>>;
+
+    /// Remove a standing order. Its purchases are kept.
+    ///
+    /// Returns `false` if there was no such order.
+    fn remove(&self, id: u64) -> Result<bool>;
+
+    /// Record content bought by a standing order and add the amount to
+    /// what the order has spent.
+    fn record_purchase(&self, purchase: &StandingOrderPurchase) -> Result<()>;
+
+    /// Whether any standing order has bought a content hash.
+    fn is_purchased(&self, content_hash: &Hash) -> Result<bool>;
+
+    /// List purchases newest first, optionally only one order's.
+    fn purchases(&self, order_id: Option<u64>, limit: u32) -> Result<Vec<StandingOrderPurchase>>;
+}
+
+// =============================================================================
+// Activity Digest Storage
+// =============================================================================
+
+/// Storage backing the activity digests: settlement failures and the
+/// periods already reported.
+pub trait DigestStore {
+    /// Record a settlement batch that failed on-chain.
+    fn record_settlement_failure(&self, failure: &SettlementFailure) -> Result<()>;
+
+    /// Settlement failures in `[from, to)`, oldest first.
+    fn settlement_failures(&self, from: Timestamp, to: Timestamp)
+        -> Result<Vec<SettlementFailure>>;
+
+    /// End of the last period a digest was sent for, if any.
+    fn sent_through(&self, period: DigestPeriod) -> Result<Option<Timestamp>>;
+
+    /// Record that digests were sent up to `through`.
+    fn set_sent_through(&self, period: DigestPeriod, through: Timestamp) -> Result<()>;
+}
+
+// =============================================================================
+// Content Availability Storage
+// =============================================================================
+
+/// Storage for the outcome of availability checks of our content.
+pub trait AvailabilityStore {
+    /// Record a check of a content hash and return its updated record.
+    fn record_check(&self, check: &AvailabilityCheck) -> Result<AvailabilityRecord>;
+
+    /// Get the record of a content hash, if it was ever checked.
+    fn get(&self, hash: &Hash) -> Result<Option<AvailabilityRecord>>;
+
+    /// List records, content failing the most consecutive checks first.
+    fn list(&self) -> Result<Vec<AvailabilityRecord>>;
+
+    /// Forget a content hash's record.
+    ///
+    /// Returns `false` if it was never checked.
+    fn remove(&self, hash: &Hash) -> Result<bool>;
+}
+
+// =============================================================================
+// Tenant Store
+// =============================================================================
+
+/// Storage for tenants, their purchases and the content published for them.
+///
+/// Only a hash of each tenant's API key is stored.
+pub trait TenantStore {
+    /// Add a tenant authenticated by `key_hash`.
+    fn create(&self, tenant: &Tenant, key_hash: &Hash) -> Result<()>;
+
+    /// Get a tenant by ID.
+    fn get(&self, id: &str) -> Result<Option<Tenant>>;
+
+    /// Get the tenant whose API key hashes to `key_hash`.
+    fn by_key(&self, key_hash: &Hash) -> Result<Option<Tenant>>;
+
+    /// List tenants, oldest first.
+    fn list(&self) -> Result<Vec<Tenant>>;
+
+    /// Set or clear a tenant's budget.
+    fn set_budget(&self, id: &str, budget: Option<Amount>) -> Result<()>;
+
+    /// Replace a tenant's API key.
+    fn set_key(&self, id: &str, key_hash: &Hash) -> Result<()>;
+
+    /// Remove a tenant and its content attributions.
+    ///
+    /// Its purchases stay on record. Returns `false` if there was none.
+    fn remove(&self, id: &str) -> Result<bool>;
+
+    /// Record a purchase and add its amount to the tenant's spending.
+    ///
+    /// Returns `false`, charging nothing, if its payment ID was already
+    /// recorded.
+    fn record_purchase(&self, purchase: &TenantPurchase) -> Result<bool>;
+
+    /// Purchases, newest first, optionally for one tenant.
+    fn purchases(&self, tenant: Option<&str>, limit: u32) -> Result<Vec<TenantPurchase>>;
+
+    /// The tenant a payment was made for, if any.
+    fn purchase_tenant(&self, payment_id: &Hash) -> Result<Option<String>>;
+
+    /// Attribute our content to a tenant, or clear its attribution with `None`.
+    fn assign_content(&self, content_hash: &Hash, tenant: Option<&str>) -> Result<()>;
+
+    /// The tenant our content is attributed to, if any.
+    fn content_tenant(&self, content_hash: &Hash) -> Result<Option<String>>;
+
+    /// Content attributed to a tenant.
+    fn content(&self, tenant: &str) -> Result<Vec<Hash>>;
+
+    /// Total paid for queries of the content attributed to a tenant.
+    fn earnings(&self, tenant: &str) -> Result<Amount>;
 }
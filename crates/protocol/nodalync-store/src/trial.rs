@@ -0,0 +1,138 @@
+//! Free trial read storage.
+//!
+//! Counts the free reads of paid content served to each requester under
+//! the content's trial policy, so each gets only the reads it offers.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::{Hash, PeerId, Timestamp};
+
+use crate::error::{Result, StoreError};
+use crate::traits::TrialStore;
+
+/// SQLite-based trial read store.
+pub struct SqliteTrialStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteTrialStore {
+    /// Create a new trial read store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+}
+
+impl TrialStore for SqliteTrialStore {
+    fn reads(&self, content_hash: &Hash, peer: &PeerId) -> Result<u32> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let reads: Option<i64> = conn
+            .prepare_cached(
+                "SELECT reads FROM trial_reads WHERE content_hash = ?1 AND peer_id = ?2",
+            )?
+            .query_row(params![content_hash.0.to_vec(), peer.0.to_vec()], |row| {
+                row.get(0)
+            })
+            .optional()?;
+
+        Ok(reads.unwrap_or(0) as u32)
+    }
+
+    fn record_read(&self, content_hash: &Hash, peer: &PeerId, at: Timestamp) -> Result<u32> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        conn.execute(
+            "INSERT INTO trial_reads (content_hash, peer_id, reads, last_read_at)
+             VALUES (?1, ?2, 1, ?3)
+             ON CONFLICT(content_hash, peer_id)
+             DO UPDATE SET reads = reads + 1, last_read_at = excluded.last_read_at",
+            params![content_hash.0.to_vec(), peer.0.to_vec(), at as i64],
+        )?;
+        let reads: i64 = conn
+            .prepare_cached(
+                "SELECT reads FROM trial_reads WHERE content_hash = ?1 AND peer_id = ?2",
+            )?
+            .query_row(params![content_hash.0.to_vec(), peer.0.to_vec()], |row| {
+                row.get(0)
+            })?;
+
+        Ok(reads as u32)
+    }
+
+    fn readers(&self, content_hash: &Hash) -> Result<u32> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let count: i64 = conn
+            .prepare_cached("SELECT COUNT(*) FROM trial_reads WHERE content_hash = ?1")?
+            .query_row([content_hash.0.to_vec()], |row| row.get(0))?;
+
+        Ok(count as u32)
+    }
+
+    fn reset(&self, content_hash: &Hash) -> Result<usize> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let removed = conn.execute(
+            "DELETE FROM trial_reads WHERE content_hash = ?1",
+            [content_hash.0.to_vec()],
+        )?;
+
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+
+    fn setup_store() -> SqliteTrialStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteTrialStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    #[test]
+    fn test_record_and_reset() {
+        let store = setup_store();
+        let hash = content_hash(b"paid content");
+        let other = content_hash(b"other content");
+        let alice = test_peer_id();
+        let bob = test_peer_id();
+
+        assert_eq!(store.reads(&hash, &alice).unwrap(), 0);
+        assert_eq!(store.record_read(&hash, &alice, 100).unwrap(), 1);
+        assert_eq!(store.record_read(&hash, &alice, 200).unwrap(), 2);
+        assert_eq!(store.record_read(&hash, &bob, 300).unwrap(), 1);
+        assert_eq!(store.record_read(&other, &alice, 400).unwrap(), 1);
+
+        // Reads are counted per content and peer
+        assert_eq!(store.reads(&hash, &alice).unwrap(), 2);
+        assert_eq!(store.reads(&hash, &bob).unwrap(), 1);
+        assert_eq!(store.readers(&hash).unwrap(), 2);
+
+        assert_eq!(store.reset(&hash).unwrap(), 2);
+        assert_eq!(store.reads(&hash, &alice).unwrap(), 0);
+        assert_eq!(store.readers(&hash).unwrap(), 0);
+        assert_eq!(store.reads(&other, &alice).unwrap(), 1);
+    }
+}
@@ -3,9 +3,9 @@
 //! This module defines types used by the storage layer that are not
 //! part of the core protocol types.
 
-use nodalync_crypto::{Hash, PeerId, PublicKey, Timestamp};
-use nodalync_types::{Amount, ContentType, Visibility};
-use nodalync_wire::payload::PaymentReceipt;
+use nodalync_crypto::{Hash, PeerId, PublicKey, Signature, Timestamp};
+use nodalync_types::{Amount, ContentType, Group, Invoice, Visibility};
+use nodalync_wire::payload::{Capability, PaymentReceipt, SearchFilters};
 use serde::{Deserialize, Serialize};
 
 /// Filter criteria for listing manifests.
@@ -30,6 +30,17 @@ pub struct ManifestFilter {
     pub owner: Option<PeerId>,
     /// Filter by text query (searches title, description, tags).
     pub text_query: Option<String>,
+    /// Filter by any of several content types.
+    pub content_types: Option<Vec<ContentType>>,
+    /// Filter by price (minimum).
+    pub min_price: Option<Amount>,
+    /// Filter by price (maximum).
+    pub max_price: Option<Amount>,
+    /// Filter by tags (all must be present, case-insensitive).
+    ///
+    /// A tag also matches its descendants, so `science` matches content
+    /// tagged `science/biology`.
+    pub tags: Option<Vec<String>>,
 }
 
 impl ManifestFilter {
@@ -85,6 +96,44 @@ impl ManifestFilter {
         self.text_query = Some(query.into());
         self
     }
+
+    /// Filter by any of several content types.
+    pub fn with_content_types(mut self, content_types: Vec<ContentType>) -> Self {
+        self.content_types = Some(content_types);
+        self
+    }
+
+    /// Filter by minimum price.
+    pub fn min_price(mut self, price: Amount) -> Self {
+        self.min_price = Some(price);
+        self
+    }
+
+    /// Filter by maximum price.
+    pub fn max_price(mut self, price: Amount) -> Self {
+        self.max_price = Some(price);
+        self
+    }
+
+    /// Require a tag (case-insensitive).
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.get_or_insert_with(Vec::new).push(tag.into());
+        self
+    }
+
+    /// Apply the filters from a SEARCH request.
+    ///
+    /// `min_reputation` has no manifest equivalent and is ignored.
+    pub fn with_search_filters(mut self, filters: &SearchFilters) -> Self {
+        self.content_types = filters.content_types.clone();
+        self.min_price = filters.min_price;
+        self.max_price = filters.max_price;
+        self.created_after = filters.created_after;
+        self.created_before = filters.created_before;
+        self.tags = filters.tags.clone();
+        self.owner = filters.owner;
+        self
+    }
 }
 
 /// Cached content entry.
@@ -129,6 +178,64 @@ impl CachedContent {
     }
 }
 
+/// The payment receipt of a query, without the content it paid for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct QueryReceipt {
+    /// Content hash that was queried.
+    pub hash: Hash,
+    /// Peer that served the content.
+    pub source_peer: PeerId,
+    /// Timestamp when the content was queried.
+    pub queried_at: Timestamp,
+    /// The provider's payment receipt.
+    pub receipt: PaymentReceipt,
+}
+
+/// A paid query we served, entitling its requester to re-download the
+/// content with the receipt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entitlement {
+    /// Payment ID of the receipt we issued.
+    pub payment_id: Hash,
+    /// Content hash queried.
+    pub content_hash: Hash,
+    /// Peer that paid.
+    pub peer: PeerId,
+    /// Amount paid.
+    pub amount: Amount,
+    /// When the query was served (ms).
+    pub granted_at: Timestamp,
+    /// Re-downloads served so far.
+    pub redemptions: u32,
+    /// When it was last re-downloaded (ms).
+    pub last_redeemed_at: Option<Timestamp>,
+}
+
+/// A channel dispute we are party to, and how far it has got.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisputeRecord {
+    /// Disputed channel.
+    pub channel_id: Hash,
+    /// Channel counterparty.
+    pub peer: PeerId,
+    /// Whether we opened the dispute.
+    pub opened_by_us: bool,
+    /// When the dispute was opened on-chain (ms).
+    pub started_at: Timestamp,
+    /// When the dispute period ends; counter-disputes must be submitted
+    /// before then (ms).
+    pub deadline: Timestamp,
+    /// Nonce of the highest state submitted on-chain.
+    pub disputed_nonce: u64,
+    /// Transaction ID of our latest counter-dispute.
+    pub counter_tx_id: Option<String>,
+    /// When we warned that the deadline was near (ms).
+    pub warned_at: Option<Timestamp>,
+    /// When the dispute was seen to be over (ms).
+    pub closed_at: Option<Timestamp>,
+}
+
 /// A distribution waiting to be settled on-chain.
 ///
 /// These are created when queries are processed and payments need
@@ -167,6 +274,537 @@ impl QueuedDistribution {
     }
 }
 
+/// A signed channel state written ahead of the channel row.
+///
+/// Every state change is checkpointed before the `channels` row is updated,
+/// so a crash between the two can be rolled forward on the next startup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ChannelCheckpoint {
+    /// Channel this state belongs to.
+    pub channel_id: Hash,
+    /// Counterparty of the channel.
+    pub peer_id: PeerId,
+    /// Channel nonce at this state.
+    pub nonce: u64,
+    /// Our balance at this state.
+    pub my_balance: Amount,
+    /// Counterparty's balance at this state.
+    pub their_balance: Amount,
+    /// Our signature over the state (close message format).
+    pub signature: Signature,
+    /// When the state was produced.
+    pub created_at: Timestamp,
+}
+
+impl ChannelCheckpoint {
+    /// Create a new channel checkpoint.
+    pub fn new(
+        channel_id: Hash,
+        peer_id: PeerId,
+        nonce: u64,
+        my_balance: Amount,
+        their_balance: Amount,
+        signature: Signature,
+        created_at: Timestamp,
+    ) -> Self {
+        Self {
+            channel_id,
+            peer_id,
+            nonce,
+            my_balance,
+            their_balance,
+            signature,
+            created_at,
+        }
+    }
+}
+
+/// Direction of a payment on a channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentDirection {
+    /// Payment signed by this node.
+    Sent,
+    /// Payment accepted from the counterparty.
+    Received,
+}
+
+/// Highest payment nonces used on a channel in each direction.
+///
+/// Tracked separately from the channel row so replayed or reused nonces
+/// are caught even if the node crashed before the channel was updated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PaymentNonces {
+    /// Channel the nonces belong to.
+    pub channel_id: Hash,
+    /// Counterparty of the channel.
+    pub peer_id: PeerId,
+    /// Highest nonce on a payment we signed.
+    pub last_sent: u64,
+    /// Highest nonce on a payment we accepted.
+    pub last_received: u64,
+    /// When a nonce was last recorded.
+    pub updated_at: Timestamp,
+}
+
+impl PaymentNonces {
+    /// Highest nonce recorded for a direction.
+    pub fn last(&self, direction: PaymentDirection) -> u64 {
+        match direction {
+            PaymentDirection::Sent => self.last_sent,
+            PaymentDirection::Received => self.last_received,
+        }
+    }
+
+    /// Highest nonce recorded in either direction.
+    pub fn highest(&self) -> u64 {
+        self.last_sent.max(self.last_received)
+    }
+}
+
+/// Whether an invoice was issued by this node or received from a payee.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvoiceDirection {
+    /// We are the payee.
+    Issued,
+    /// We are asked to pay.
+    Received,
+}
+
+impl InvoiceDirection {
+    /// Get the string representation stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InvoiceDirection::Issued => "issued",
+            InvoiceDirection::Received => "received",
+        }
+    }
+
+    /// Parse from the database string representation.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "issued" => Some(InvoiceDirection::Issued),
+            "received" => Some(InvoiceDirection::Received),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for InvoiceDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Payment status of an invoice.
+///
+/// Expiry is not a stored status: an unpaid invoice is expired once its
+/// `expires_at` has passed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvoiceStatus {
+    /// Not paid yet.
+    Unpaid,
+    /// Paid through a payment channel.
+    Paid,
+}
+
+impl InvoiceStatus {
+    /// Get the string representation stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InvoiceStatus::Unpaid => "unpaid",
+            InvoiceStatus::Paid => "paid",
+        }
+    }
+
+    /// Parse from the database string representation.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "unpaid" => Some(InvoiceStatus::Unpaid),
+            "paid" => Some(InvoiceStatus::Paid),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for InvoiceStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An invoice tracked by this node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct InvoiceRecord {
+    /// The signed invoice.
+    pub invoice: Invoice,
+    /// Whether we issued or received it.
+    pub direction: InvoiceDirection,
+    /// Payment status.
+    pub status: InvoiceStatus,
+    /// Who paid it, once paid.
+    pub payer: Option<PeerId>,
+    /// ID of the channel payment that paid it.
+    pub payment_id: Option<Hash>,
+    /// When it was paid.
+    pub paid_at: Option<Timestamp>,
+}
+
+impl InvoiceRecord {
+    /// Create a record for an unpaid invoice.
+    pub fn new(invoice: Invoice, direction: InvoiceDirection) -> Self {
+        Self {
+            invoice,
+            direction,
+            status: InvoiceStatus::Unpaid,
+            payer: None,
+            payment_id: None,
+            paid_at: None,
+        }
+    }
+
+    /// Check if the invoice is unpaid and past its expiry.
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        self.status == InvoiceStatus::Unpaid && self.invoice.is_expired(now)
+    }
+}
+
+/// A group membership list held by this node.
+///
+/// Groups we own are authoritative; groups owned by other peers are cached
+/// copies refreshed once `fetched_at` is older than the cache TTL.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredGroup {
+    /// The signed membership list.
+    pub group: Group,
+    /// When this copy was created or last fetched from the owner.
+    pub fetched_at: Timestamp,
+}
+
+impl StoredGroup {
+    /// Create a stored group.
+    pub fn new(group: Group, fetched_at: Timestamp) -> Self {
+        Self { group, fetched_at }
+    }
+
+    /// Check if a cached copy is older than `ttl_ms`.
+    pub fn is_stale(&self, now: Timestamp, ttl_ms: u64) -> bool {
+        now.saturating_sub(self.fetched_at) >= ttl_ms
+    }
+}
+
+/// Moderation status of reported content on this node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationStatus {
+    /// Reported and awaiting review.
+    Pending,
+    /// Hidden automatically after enough reports from reputable peers.
+    AutoHidden,
+    /// Hidden by the node operator.
+    Hidden,
+    /// Reviewed and kept visible by the node operator.
+    Allowed,
+}
+
+impl ModerationStatus {
+    /// Get the string representation stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModerationStatus::Pending => "pending",
+            ModerationStatus::AutoHidden => "auto_hidden",
+            ModerationStatus::Hidden => "hidden",
+            ModerationStatus::Allowed => "allowed",
+        }
+    }
+
+    /// Parse from the database string representation.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(ModerationStatus::Pending),
+            "auto_hidden" => Some(ModerationStatus::AutoHidden),
+            "hidden" => Some(ModerationStatus::Hidden),
+            "allowed" => Some(ModerationStatus::Allowed),
+            _ => None,
+        }
+    }
+
+    /// Check if content with this status is hidden.
+    pub fn is_hidden(&self) -> bool {
+        matches!(
+            self,
+            ModerationStatus::AutoHidden | ModerationStatus::Hidden
+        )
+    }
+
+    /// Check if the status still needs an operator decision.
+    ///
+    /// Auto-hidden content stays in the review queue so the operator can
+    /// confirm or overturn the automatic decision.
+    pub fn needs_review(&self) -> bool {
+        matches!(
+            self,
+            ModerationStatus::Pending | ModerationStatus::AutoHidden
+        )
+    }
+}
+
+impl std::fmt::Display for ModerationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Reported content and its moderation status.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModerationEntry {
+    /// The reported content.
+    pub content_hash: Hash,
+    /// Current moderation status.
+    pub status: ModerationStatus,
+    /// Number of distinct peers that reported it.
+    pub report_count: u32,
+    /// When the status last changed.
+    pub updated_at: Timestamp,
+}
+
+/// Kind of on-chain transaction recorded in the local wallet history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WalletTransactionKind {
+    /// Deposit into the settlement contract.
+    Deposit,
+    /// Withdrawal from the settlement contract.
+    Withdraw,
+    /// Batch settlement submitted by this node.
+    Settlement,
+    /// Publisher bond posted from the contract balance.
+    Bond,
+}
+
+impl WalletTransactionKind {
+    /// Get the string representation stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WalletTransactionKind::Deposit => "deposit",
+            WalletTransactionKind::Withdraw => "withdraw",
+            WalletTransactionKind::Settlement => "settlement",
+            WalletTransactionKind::Bond => "bond",
+        }
+    }
+
+    /// Parse from the database string representation.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "deposit" => Some(WalletTransactionKind::Deposit),
+            "withdraw" => Some(WalletTransactionKind::Withdraw),
+            "settlement" => Some(WalletTransactionKind::Settlement),
+            "bond" => Some(WalletTransactionKind::Bond),
+            _ => None,
+        }
+    }
+}
+
+/// An on-chain transaction submitted by this node.
+///
+/// Kept locally so wallet views can list recent activity without
+/// querying the chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct WalletTransaction {
+    /// On-chain transaction ID.
+    pub transaction_id: String,
+    /// What the transaction did.
+    pub kind: WalletTransactionKind,
+    /// Amount moved (in tinybars).
+    pub amount: Amount,
+    /// When the transaction was submitted.
+    pub timestamp: Timestamp,
+}
+
+impl WalletTransaction {
+    /// Create a new wallet transaction record.
+    pub fn new(
+        transaction_id: impl Into<String>,
+        kind: WalletTransactionKind,
+        amount: Amount,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            transaction_id: transaction_id.into(),
+            kind,
+            amount,
+            timestamp,
+        }
+    }
+}
+
+/// Whether an access served a preview or paid content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessKind {
+    /// Free preview (L1 mentions and metadata).
+    Preview,
+    /// Paid query for the full content.
+    Query,
+}
+
+impl AccessKind {
+    /// Get the integer representation stored in the database.
+    pub fn as_i64(&self) -> i64 {
+        match self {
+            AccessKind::Preview => 0,
+            AccessKind::Query => 1,
+        }
+    }
+
+    /// Parse from the database integer representation.
+    pub fn from_i64(value: i64) -> Option<Self> {
+        match value {
+            0 => Some(AccessKind::Preview),
+            1 => Some(AccessKind::Query),
+            _ => None,
+        }
+    }
+}
+
+/// Who made a recorded access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessRequester {
+    /// The requesting peer's ID.
+    Peer(PeerId),
+    /// An opaque hash of the requesting peer's ID, for publishers that
+    /// don't keep consumer identities.
+    Hashed(Hash),
+}
+
+/// A single access to local content, recorded for publisher analytics.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct AccessRecord {
+    /// Content that was accessed.
+    pub content_hash: Hash,
+    /// Who accessed it.
+    pub requester: AccessRequester,
+    /// Preview or paid query.
+    pub kind: AccessKind,
+    /// Amount paid (0 for previews).
+    pub amount: Amount,
+    /// When the access was served.
+    pub timestamp: Timestamp,
+}
+
+impl AccessRecord {
+    /// Create a new access record.
+    pub fn new(
+        content_hash: Hash,
+        requester: AccessRequester,
+        kind: AccessKind,
+        amount: Amount,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            content_hash,
+            requester,
+            kind,
+            amount,
+            timestamp,
+        }
+    }
+}
+
+/// A usage report received for local content.
+///
+/// Reports are anonymized: the reporting peer is not stored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct UsageRecord {
+    /// Content the report is about.
+    pub content_hash: Hash,
+    /// Bytes of the content the consumer read.
+    pub bytes_read: u64,
+    /// Size of the model context the content was used in, in tokens.
+    pub context_tokens: Option<u32>,
+    /// Outcome rating (1-5).
+    pub rating: Option<u8>,
+    /// When the report was received.
+    pub timestamp: Timestamp,
+}
+
+/// A kind of request counted towards a content hash's popularity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PopularityKind {
+    /// A preview of the content.
+    Preview,
+    /// A query for the full content.
+    Query,
+    /// A search the content matched.
+    SearchHit,
+}
+
+impl PopularityKind {
+    /// How much one request adds to the popularity score.
+    ///
+    /// A query counts for more than a preview, and a search hit for less:
+    /// matching a search says little about whether anyone wants the content.
+    pub fn weight(&self) -> f64 {
+        match self {
+            PopularityKind::Preview => 1.0,
+            PopularityKind::Query => 3.0,
+            PopularityKind::SearchHit => 0.25,
+        }
+    }
+}
+
+/// Request counters and decaying popularity score of a content hash.
+///
+/// The hash may be local, cached, or only known from announcements.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PopularityRecord {
+    /// Content the requests were for.
+    pub hash: Hash,
+    /// Previews counted.
+    pub previews: u64,
+    /// Queries counted.
+    pub queries: u64,
+    /// Searches the content matched.
+    pub search_hits: u64,
+    /// Weighted request score as of `updated_at` (as of the time asked,
+    /// when listed by [`PopularityStore::top`](crate::PopularityStore::top)).
+    pub score: f64,
+    /// When the last request was counted.
+    pub updated_at: Timestamp,
+}
+
+impl PopularityRecord {
+    /// The score decayed to `now`, halving every `half_life_ms`.
+    pub fn score_at(&self, now: Timestamp, half_life_ms: u64) -> f64 {
+        decay(
+            self.score,
+            now.saturating_sub(self.updated_at),
+            half_life_ms,
+        )
+    }
+}
+
+/// Decay a score over `elapsed_ms`, halving every `half_life_ms`.
+///
+/// A zero half-life doesn't decay.
+pub(crate) fn decay(score: f64, elapsed_ms: u64, half_life_ms: u64) -> f64 {
+    if half_life_ms == 0 {
+        return score;
+    }
+    score * 0.5f64.powf(elapsed_ms as f64 / half_life_ms as f64)
+}
+
 /// Information about a known peer.
 ///
 /// Spec §5.1: Stores peer metadata including network addresses,
@@ -184,6 +822,14 @@ pub struct PeerInfo {
     pub last_seen: Timestamp,
     /// Reputation score (can be negative).
     pub reputation: i64,
+    /// Capabilities the peer advertised, or `None` before it has
+    /// advertised any.
+    #[serde(default)]
+    pub capabilities: Option<Vec<Capability>>,
+    /// Round-trip times from pinging the peer, or `None` before it has
+    /// been probed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency: Option<LatencyStats>,
 }
 
 impl PeerInfo {
@@ -200,6 +846,8 @@ impl PeerInfo {
             addresses,
             last_seen,
             reputation: 0,
+            capabilities: None,
+            latency: None,
         }
     }
 
@@ -209,6 +857,22 @@ impl PeerInfo {
         self
     }
 
+    /// Record the capabilities the peer advertised.
+    pub fn with_capabilities(mut self, capabilities: Vec<Capability>) -> Self {
+        self.capabilities = Some(capabilities);
+        self
+    }
+
+    /// Whether the peer may be relied on for `capability`.
+    ///
+    /// Peers that haven't advertised capabilities are assumed to support
+    /// everything, as nodes did before capabilities were advertised.
+    pub fn supports(&self, capability: Capability) -> bool {
+        self.capabilities
+            .as_ref()
+            .is_none_or(|capabilities| capabilities.contains(&capability))
+    }
+
     /// Add an address to the peer.
     pub fn add_address(&mut self, address: String) {
         if !self.addresses.contains(&address) {
@@ -225,6 +889,726 @@ impl PeerInfo {
     pub fn adjust_reputation(&mut self, delta: i64) {
         self.reputation = self.reputation.saturating_add(delta);
     }
+
+    /// Get the peer's `did:key` identifier.
+    pub fn did(&self) -> String {
+        nodalync_crypto::did_from_public_key(&self.public_key)
+    }
+
+    /// The peer's QoS score, if it has been probed.
+    pub fn qos_score(&self) -> Option<f64> {
+        self.latency.as_ref().and_then(LatencyStats::qos_score)
+    }
+}
+
+/// Number of recent probes a peer's latency statistics are taken over.
+pub const LATENCY_WINDOW: usize = 32;
+
+/// Round trip at which a peer with no jitter or loss scores 0.5, in
+/// milliseconds.
+pub const QOS_REFERENCE_LATENCY_MS: f64 = 200.0;
+
+/// Round-trip times measured by pinging a peer.
+///
+/// Statistics cover the last [`LATENCY_WINDOW`] probes, so they follow
+/// the peer's current connection rather than its whole history.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct LatencyStats {
+    /// Round trip of each recent probe in milliseconds, oldest first;
+    /// `None` for a probe that went unanswered.
+    pub samples: Vec<Option<u32>>,
+    /// Probes sent since the peer was first probed.
+    pub probes: u64,
+    /// Probes that went unanswered since the peer was first probed.
+    pub lost: u64,
+    /// When the peer was last probed.
+    pub last_probe: Timestamp,
+}
+
+impl LatencyStats {
+    /// Record a probe: its round trip, or `None` if it went unanswered.
+    pub fn record(&mut self, rtt_ms: Option<u32>, timestamp: Timestamp) {
+        self.samples.push(rtt_ms);
+        if self.samples.len() > LATENCY_WINDOW {
+            let excess = self.samples.len() - LATENCY_WINDOW;
+            self.samples.drain(..excess);
+        }
+        self.probes += 1;
+        if rtt_ms.is_none() {
+            self.lost += 1;
+        }
+        self.last_probe = timestamp;
+    }
+
+    fn answered(&self) -> impl Iterator<Item = u32> + '_ {
+        self.samples.iter().flatten().copied()
+    }
+
+    /// Round trip below which `percentile` percent of recent answers fell
+    /// (nearest rank), in milliseconds.
+    pub fn percentile(&self, percentile: f64) -> Option<u32> {
+        let mut answered: Vec<u32> = self.answered().collect();
+        if answered.is_empty() {
+            return None;
+        }
+        answered.sort_unstable();
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * answered.len() as f64).ceil();
+        let index = (rank as usize).saturating_sub(1).min(answered.len() - 1);
+        Some(answered[index])
+    }
+
+    /// Mean round trip of recent answers, in milliseconds.
+    pub fn mean_ms(&self) -> Option<f64> {
+        let (count, total) = self
+            .answered()
+            .fold((0u64, 0u64), |(n, sum), rtt| (n + 1, sum + rtt as u64));
+        (count > 0).then(|| total as f64 / count as f64)
+    }
+
+    /// Mean difference between consecutive recent answers, in
+    /// milliseconds; `None` with fewer than two.
+    pub fn jitter_ms(&self) -> Option<f64> {
+        let answered: Vec<u32> = self.answered().collect();
+        if answered.len() < 2 {
+            return None;
+        }
+        let total: u64 = answered
+            .windows(2)
+            .map(|pair| pair[0].abs_diff(pair[1]) as u64)
+            .sum();
+        Some(total as f64 / (answered.len() - 1) as f64)
+    }
+
+    /// Share of recent probes that went unanswered, in `[0, 1]`.
+    pub fn loss_rate(&self) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        let lost = self.samples.iter().filter(|s| s.is_none()).count();
+        lost as f64 / self.samples.len() as f64
+    }
+
+    /// Quality of service in `[0, 1]`, higher is better.
+    ///
+    /// The share of probes answered, scaled down as the 90th percentile
+    /// round trip plus jitter grows past [`QOS_REFERENCE_LATENCY_MS`].
+    /// `None` before the first probe.
+    pub fn qos_score(&self) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let Some(p90) = self.percentile(90.0) else {
+            return Some(0.0);
+        };
+        let delay = p90 as f64 + self.jitter_ms().unwrap_or(0.0);
+        Some(
+            (1.0 - self.loss_rate()) * QOS_REFERENCE_LATENCY_MS
+                / (QOS_REFERENCE_LATENCY_MS + delay),
+        )
+    }
+}
+
+/// A registered tag with the number of local manifests filed under it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct TagInfo {
+    /// Normalized tag, e.g. `science/biology`.
+    pub tag: String,
+    /// Parent tag, or `None` for a top-level tag.
+    pub parent: Option<String>,
+    /// Manifests tagged with this tag or one of its descendants.
+    pub content_count: u64,
+}
+
+/// An account in the economic ledger.
+///
+/// Asset and expense accounts grow with debits; liability, equity and
+/// income accounts grow with credits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerAccount {
+    /// Funds outside the node's accounts (equity): deposits into the
+    /// settlement contract come from here and withdrawals go back to it.
+    External,
+    /// Balance deposited in the settlement contract (asset).
+    Contract,
+    /// Our side of payment channels (asset).
+    Channels,
+    /// Revenue owed to other contributors until it is settled (liability).
+    Payable,
+    /// Our share of query revenue as a root contributor, and invoices
+    /// paid to us (income).
+    Revenue,
+    /// Synthesis fees earned as content owner (income).
+    Fees,
+    /// Payments for queried content and invoices (expense).
+    QuerySpend,
+}
+
+impl LedgerAccount {
+    /// All accounts, in chart-of-accounts order.
+    pub const ALL: [LedgerAccount; 7] = [
+        LedgerAccount::External,
+        LedgerAccount::Contract,
+        LedgerAccount::Channels,
+        LedgerAccount::Payable,
+        LedgerAccount::Revenue,
+        LedgerAccount::Fees,
+        LedgerAccount::QuerySpend,
+    ];
+
+    /// Get the string representation stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LedgerAccount::External => "external",
+            LedgerAccount::Contract => "contract",
+            LedgerAccount::Channels => "channels",
+            LedgerAccount::Payable => "payable",
+            LedgerAccount::Revenue => "revenue",
+            LedgerAccount::Fees => "fees",
+            LedgerAccount::QuerySpend => "query_spend",
+        }
+    }
+
+    /// Parse from the database string representation.
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|account| account.as_str() == s)
+    }
+
+    /// Whether the account's balance grows with debits (assets and expenses).
+    pub fn is_debit_normal(&self) -> bool {
+        matches!(
+            self,
+            LedgerAccount::Contract | LedgerAccount::Channels | LedgerAccount::QuerySpend
+        )
+    }
+}
+
+impl std::fmt::Display for LedgerAccount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The economic event a ledger transaction records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerEvent {
+    /// Deposit into the settlement contract.
+    ContractDeposit,
+    /// Withdrawal from the settlement contract.
+    ContractWithdrawal,
+    /// Funds locked in a payment channel we opened or accepted.
+    ChannelDeposit,
+    /// Our channel balance released when a channel closed.
+    ChannelClose,
+    /// Payment for querying another node's content.
+    QueryPaid,
+    /// Payment received for a query of our content.
+    RevenueReceived,
+    /// Synthesis fee received as the content owner.
+    Fee,
+    /// Revenue paid out to other contributors by settlement.
+    SettlementPayout,
+    /// Payment of an invoice issued by another node.
+    InvoicePaid,
+    /// Payment received for an invoice we issued.
+    InvoiceReceived,
+    /// Automatic deposit topping up a low settlement balance.
+    AutoTopUp,
+    /// Payment to a relay for carrying one of our queries.
+    RelayPaid,
+    /// Payment received for relaying another node's query.
+    RelayReceived,
+}
+
+impl LedgerEvent {
+    /// Get the string representation stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LedgerEvent::ContractDeposit => "contract_deposit",
+            LedgerEvent::ContractWithdrawal => "contract_withdrawal",
+            LedgerEvent::ChannelDeposit => "channel_deposit",
+            LedgerEvent::ChannelClose => "channel_close",
+            LedgerEvent::QueryPaid => "query_paid",
+            LedgerEvent::RevenueReceived => "revenue_received",
+            LedgerEvent::Fee => "fee",
+            LedgerEvent::SettlementPayout => "settlement_payout",
+            LedgerEvent::InvoicePaid => "invoice_paid",
+            LedgerEvent::InvoiceReceived => "invoice_received",
+            LedgerEvent::AutoTopUp => "auto_top_up",
+            LedgerEvent::RelayPaid => "relay_paid",
+            LedgerEvent::RelayReceived => "relay_received",
+        }
+    }
+
+    /// Parse from the database string representation.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "contract_deposit" => Some(LedgerEvent::ContractDeposit),
+            "contract_withdrawal" => Some(LedgerEvent::ContractWithdrawal),
+            "channel_deposit" => Some(LedgerEvent::ChannelDeposit),
+            "channel_close" => Some(LedgerEvent::ChannelClose),
+            "query_paid" => Some(LedgerEvent::QueryPaid),
+            "revenue_received" => Some(LedgerEvent::RevenueReceived),
+            "fee" => Some(LedgerEvent::Fee),
+            "settlement_payout" => Some(LedgerEvent::SettlementPayout),
+            "invoice_paid" => Some(LedgerEvent::InvoicePaid),
+            "invoice_received" => Some(LedgerEvent::InvoiceReceived),
+            "auto_top_up" => Some(LedgerEvent::AutoTopUp),
+            "relay_paid" => Some(LedgerEvent::RelayPaid),
+            "relay_received" => Some(LedgerEvent::RelayReceived),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for LedgerEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One side of a ledger transaction: a debit or credit to an account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct LedgerPosting {
+    /// Account posted to.
+    pub account: LedgerAccount,
+    /// Amount debited (in tinybars).
+    pub debit: Amount,
+    /// Amount credited (in tinybars).
+    pub credit: Amount,
+}
+
+impl LedgerPosting {
+    /// Debit an account.
+    pub fn debit(account: LedgerAccount, amount: Amount) -> Self {
+        Self {
+            account,
+            debit: amount,
+            credit: 0,
+        }
+    }
+
+    /// Credit an account.
+    pub fn credit(account: LedgerAccount, amount: Amount) -> Self {
+        Self {
+            account,
+            debit: 0,
+            credit: amount,
+        }
+    }
+}
+
+/// A balanced set of postings recording one economic event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct LedgerTransaction {
+    /// What happened.
+    pub event: LedgerEvent,
+    /// What the event refers to: a content hash, channel ID, payment ID or
+    /// on-chain transaction ID.
+    pub reference: Option<String>,
+    /// When the event happened.
+    pub timestamp: Timestamp,
+    /// Debits and credits; total debits must equal total credits.
+    pub postings: Vec<LedgerPosting>,
+}
+
+impl LedgerTransaction {
+    /// Create a transaction with no postings.
+    pub fn new(event: LedgerEvent, reference: Option<String>, timestamp: Timestamp) -> Self {
+        Self {
+            event,
+            reference,
+            timestamp,
+            postings: Vec::new(),
+        }
+    }
+
+    /// Add a posting. Zero-amount postings are skipped.
+    pub fn with_posting(mut self, posting: LedgerPosting) -> Self {
+        if posting.debit > 0 || posting.credit > 0 {
+            self.postings.push(posting);
+        }
+        self
+    }
+
+    /// Move `amount` from `from` to `to` (debit `to`, credit `from`).
+    pub fn transfer(
+        event: LedgerEvent,
+        reference: Option<String>,
+        timestamp: Timestamp,
+        to: LedgerAccount,
+        from: LedgerAccount,
+        amount: Amount,
+    ) -> Self {
+        Self::new(event, reference, timestamp)
+            .with_posting(LedgerPosting::debit(to, amount))
+            .with_posting(LedgerPosting::credit(from, amount))
+    }
+
+    /// Whether total debits equal total credits.
+    pub fn is_balanced(&self) -> bool {
+        let debits: u128 = self.postings.iter().map(|p| p.debit as u128).sum();
+        let credits: u128 = self.postings.iter().map(|p| p.credit as u128).sum();
+        debits == credits
+    }
+}
+
+/// A stored ledger row: one posting with the account's running balance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct LedgerEntry {
+    /// ID of the transaction this entry belongs to.
+    pub transaction_id: u64,
+    /// Event recorded by the transaction.
+    pub event: LedgerEvent,
+    /// What the event refers to.
+    pub reference: Option<String>,
+    /// When the event happened.
+    pub timestamp: Timestamp,
+    /// Account posted to.
+    pub account: LedgerAccount,
+    /// Amount debited.
+    pub debit: Amount,
+    /// Amount credited.
+    pub credit: Amount,
+    /// Account balance after this entry, on the account's normal side
+    /// (see [`LedgerAccount::is_debit_normal`]). Negative when the account
+    /// is overdrawn.
+    pub balance: i64,
+}
+
+/// A category of data that a retention policy can expire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionCategory {
+    /// Content announcements received from the network, by receipt time.
+    Announcements,
+    /// Content cached from queries, by query time.
+    CachedContent,
+    /// Known peers, by when they were last seen. Peers we have a channel
+    /// with are kept.
+    PeerRecords,
+    /// Closed channels with their payments and checkpoints, by last
+    /// update, and settled payments, by payment time.
+    ChannelHistory,
+    /// Access log entries, usage reports, popularity counters and failed
+    /// settlements, by time recorded.
+    Analytics,
+}
+
+impl RetentionCategory {
+    /// All categories, in report order.
+    pub const ALL: [RetentionCategory; 5] = [
+        RetentionCategory::Announcements,
+        RetentionCategory::CachedContent,
+        RetentionCategory::PeerRecords,
+        RetentionCategory::ChannelHistory,
+        RetentionCategory::Analytics,
+    ];
+
+    /// Get the string representation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetentionCategory::Announcements => "announcements",
+            RetentionCategory::CachedContent => "cached_content",
+            RetentionCategory::PeerRecords => "peer_records",
+            RetentionCategory::ChannelHistory => "channel_history",
+            RetentionCategory::Analytics => "analytics",
+        }
+    }
+
+    /// Parse from the string representation.
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.as_str() == s)
+    }
+}
+
+impl std::fmt::Display for RetentionCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Record counts for one retention category.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionStats {
+    /// Records currently held.
+    pub records: u64,
+    /// Records older than the cutoff asked about.
+    pub expired: u64,
+    /// Timestamp of the oldest record (ms), if any.
+    pub oldest: Option<Timestamp>,
+}
+
+/// Delivery status of a webhook notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookDeliveryStatus {
+    /// Not delivered yet; retried at `next_attempt_at`.
+    Pending,
+    /// Accepted by the endpoint.
+    Delivered,
+    /// Given up on after the last attempt failed.
+    Failed,
+}
+
+impl WebhookDeliveryStatus {
+    /// Get the string representation stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookDeliveryStatus::Pending => "pending",
+            WebhookDeliveryStatus::Delivered => "delivered",
+            WebhookDeliveryStatus::Failed => "failed",
+        }
+    }
+
+    /// Parse from the database string representation.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(WebhookDeliveryStatus::Pending),
+            "delivered" => Some(WebhookDeliveryStatus::Delivered),
+            "failed" => Some(WebhookDeliveryStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for WebhookDeliveryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A webhook notification for one endpoint and its delivery state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    /// Delivery ID, assigned when queued.
+    pub id: u64,
+    /// Endpoint URL.
+    pub url: String,
+    /// Event type (e.g. `payment_received`).
+    pub event: String,
+    /// JSON body posted to the endpoint.
+    pub payload: String,
+    /// Delivery status.
+    pub status: WebhookDeliveryStatus,
+    /// Attempts made so far.
+    pub attempts: u32,
+    /// When the next attempt is due (ms).
+    pub next_attempt_at: Timestamp,
+    /// Error of the last failed attempt.
+    pub last_error: Option<String>,
+    /// When the notification was queued (ms).
+    pub created_at: Timestamp,
+    /// When it was delivered or given up on (ms).
+    pub finished_at: Option<Timestamp>,
+}
+
+/// How often an activity digest is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestPeriod {
+    /// One digest per UTC day.
+    Daily,
+    /// One digest per week, starting Monday 00:00 UTC.
+    Weekly,
+}
+
+impl DigestPeriod {
+    /// Get the string representation stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DigestPeriod::Daily => "daily",
+            DigestPeriod::Weekly => "weekly",
+        }
+    }
+
+    /// Parse from the database string representation.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "daily" => Some(DigestPeriod::Daily),
+            "weekly" => Some(DigestPeriod::Weekly),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for DigestPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A settlement batch that failed on-chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SettlementFailure {
+    /// Batch that failed. Its distributions stay queued for the next batch.
+    pub batch_id: Hash,
+    /// Total amount of the batch.
+    pub amount: Amount,
+    /// Error returned by the settlement layer.
+    pub error: String,
+    /// When the attempt failed (ms).
+    pub timestamp: Timestamp,
+}
+
+/// The outcome of one availability check of our content by a remote peer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AvailabilityCheck {
+    /// Content checked.
+    pub hash: Hash,
+    /// Peer asked to fetch it from outside.
+    pub checker: PeerId,
+    /// Whether the checker could preview the content.
+    pub reachable: bool,
+    /// Address the checker reached us on.
+    pub dialed: Option<String>,
+    /// Round trip of the checker's preview request (ms).
+    pub latency_ms: Option<u32>,
+    /// Why the check failed.
+    pub error: Option<String>,
+    /// When the check was made (ms).
+    pub checked_at: Timestamp,
+}
+
+/// The latest availability check of a content hash, with running totals.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AvailabilityRecord {
+    /// Latest check.
+    pub last: AvailabilityCheck,
+    /// Checks made.
+    pub checks: u64,
+    /// Checks that failed.
+    pub failures: u64,
+    /// Checks that failed since the last success.
+    pub consecutive_failures: u32,
+}
+
+/// A standing order: content to buy automatically as it is announced.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StandingOrder {
+    /// Order ID, assigned when created.
+    pub id: u64,
+    /// Normalized tag the content must be filed under (itself or a child).
+    pub tag: String,
+    /// Highest price paid per item.
+    pub max_price: Amount,
+    /// Lowest reputation accepted from the publisher (`None` = any).
+    pub min_reputation: Option<i64>,
+    /// Most spent by the order in total (`None` = no limit).
+    pub budget: Option<Amount>,
+    /// Spent by the order so far.
+    pub spent: Amount,
+    /// When the order was created (ms). Only announcements received since
+    /// then are bought.
+    pub created_at: Timestamp,
+}
+
+impl StandingOrder {
+    /// Budget left, or `None` without a budget.
+    pub fn remaining_budget(&self) -> Option<Amount> {
+        self.budget.map(|budget| budget.saturating_sub(self.spent))
+    }
+
+    /// Whether the order can still pay `price`.
+    pub fn can_afford(&self, price: Amount) -> bool {
+        price <= self.max_price && self.remaining_budget().is_none_or(|left| price <= left)
+    }
+}
+
+/// Content bought by a standing order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StandingOrderPurchase {
+    /// Content bought.
+    pub content_hash: Hash,
+    /// Order that bought it.
+    pub order_id: u64,
+    /// Title of the content when bought.
+    pub title: String,
+    /// Amount paid.
+    pub amount: Amount,
+    /// When it was bought (ms).
+    pub purchased_at: Timestamp,
+}
+
+/// An end-user served from this node's identity, e.g. by a hosted gateway.
+///
+/// Tenants share the node's identity, network stack and content cache, but
+/// each authenticates with its own API key and spends from its own budget.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tenant {
+    /// Tenant ID, chosen when created.
+    pub id: String,
+    /// Display name.
+    pub name: String,
+    /// Most the tenant may spend in total (`None` = no limit).
+    pub budget: Option<Amount>,
+    /// Spent by the tenant so far.
+    pub spent: Amount,
+    /// When the tenant was created (ms).
+    pub created_at: Timestamp,
+}
+
+impl Tenant {
+    /// Budget left, or `None` without a budget.
+    pub fn remaining_budget(&self) -> Option<Amount> {
+        self.budget.map(|budget| budget.saturating_sub(self.spent))
+    }
+
+    /// Whether the tenant can still pay `price`.
+    pub fn can_afford(&self, price: Amount) -> bool {
+        self.remaining_budget().is_none_or(|left| price <= left)
+    }
+}
+
+/// A query paid for on behalf of a tenant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TenantPurchase {
+    /// Payment ID of the query's receipt.
+    pub payment_id: Hash,
+    /// Tenant charged.
+    pub tenant: String,
+    /// Content queried.
+    pub content_hash: Hash,
+    /// Amount paid.
+    pub amount: Amount,
+    /// When it was bought (ms).
+    pub purchased_at: Timestamp,
+}
+
+/// A peer's queries and bytes served in one quota period.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    /// Queries served.
+    pub queries: u32,
+    /// Bytes of content served.
+    pub bytes: u64,
+}
+
+/// Content we bought, kept in our library.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LibraryEntry {
+    /// Content hash.
+    pub hash: Hash,
+    /// Content title when bought.
+    pub title: String,
+    /// Content type.
+    pub content_type: ContentType,
+    /// Amount paid.
+    pub price_paid: Amount,
+    /// Peer that served the content.
+    pub provider: PeerId,
+    /// When it was bought (ms).
+    pub purchased_at: Timestamp,
+    /// The provider's payment receipt.
+    pub receipt: PaymentReceipt,
 }
 
 #[cfg(test)]
@@ -254,6 +1638,10 @@ mod tests {
             timestamp: 1234567890,
             channel_nonce: 1,
             distributor_signature: Signature::from_bytes([0u8; 64]),
+            app_fee: 0,
+            app_fee_recipient: None,
+            referral_fee: 0,
+            referrer: None,
         }
     }
 
@@ -302,6 +1690,18 @@ mod tests {
         assert_eq!(dist.amount, 1000);
     }
 
+    #[test]
+    fn test_wallet_transaction_kind_roundtrip() {
+        for kind in [
+            WalletTransactionKind::Deposit,
+            WalletTransactionKind::Withdraw,
+            WalletTransactionKind::Settlement,
+        ] {
+            assert_eq!(WalletTransactionKind::parse(kind.as_str()), Some(kind));
+        }
+        assert_eq!(WalletTransactionKind::parse("bogus"), None);
+    }
+
     #[test]
     fn test_peer_info() {
         let (_, public_key) = generate_identity();
@@ -327,6 +1727,11 @@ mod tests {
         // Adding duplicate address should not increase count
         info.add_address("/ip4/127.0.0.1/tcp/9000".to_string());
         assert_eq!(info.addresses.len(), 2);
+
+        assert_eq!(
+            nodalync_crypto::public_key_from_did(&info.did()).unwrap(),
+            public_key
+        );
     }
 
     #[test]
@@ -343,4 +1748,94 @@ mod tests {
         info.adjust_reputation(-1);
         assert_eq!(info.reputation, i64::MIN);
     }
+
+    #[test]
+    fn test_peer_info_capabilities() {
+        let (_, public_key) = generate_identity();
+        let peer_id = peer_id_from_public_key(&public_key);
+
+        // Nothing advertised yet: assume support
+        let info = PeerInfo::new(peer_id, public_key, vec![], 1000);
+        assert!(info.supports(Capability::ChunkedTransfer));
+
+        let info = info.with_capabilities(vec![Capability::Query]);
+        assert!(info.supports(Capability::Query));
+        assert!(!info.supports(Capability::ChunkedTransfer));
+    }
+
+    #[test]
+    fn test_latency_stats() {
+        let mut stats = LatencyStats::default();
+        assert_eq!(stats.qos_score(), None);
+        assert_eq!(stats.percentile(50.0), None);
+        assert_eq!(stats.loss_rate(), 0.0);
+
+        for rtt in [Some(40), Some(60), None, Some(50), Some(250)] {
+            stats.record(rtt, 1_000);
+        }
+        assert_eq!(stats.probes, 5);
+        assert_eq!(stats.lost, 1);
+        assert_eq!(stats.last_probe, 1_000);
+        assert_eq!(stats.percentile(50.0), Some(50));
+        assert_eq!(stats.percentile(90.0), Some(250));
+        assert_eq!(stats.percentile(0.0), Some(40));
+        assert_eq!(stats.mean_ms(), Some(100.0));
+        // |60-40| + |50-60| + |250-50| over three gaps
+        assert_eq!(stats.jitter_ms(), Some(230.0 / 3.0));
+        assert!((stats.loss_rate() - 0.2).abs() < f64::EPSILON);
+
+        let score = stats.qos_score().unwrap();
+        let expected =
+            0.8 * QOS_REFERENCE_LATENCY_MS / (QOS_REFERENCE_LATENCY_MS + 250.0 + 230.0 / 3.0);
+        assert!((score - expected).abs() < 1e-9);
+
+        // A fast, steady peer scores higher; one that never answers scores 0
+        let mut fast = LatencyStats::default();
+        fast.record(Some(20), 0);
+        fast.record(Some(20), 0);
+        assert!(fast.qos_score().unwrap() > score);
+        let mut silent = LatencyStats::default();
+        silent.record(None, 0);
+        assert_eq!(silent.qos_score(), Some(0.0));
+    }
+
+    #[test]
+    fn test_latency_stats_window() {
+        let mut stats = LatencyStats::default();
+        stats.record(None, 0);
+        for i in 0..LATENCY_WINDOW as u32 {
+            stats.record(Some(i), i as u64);
+        }
+        // The lost probe fell out of the window but still counts overall
+        assert_eq!(stats.samples.len(), LATENCY_WINDOW);
+        assert_eq!(stats.loss_rate(), 0.0);
+        assert_eq!(stats.lost, 1);
+        assert_eq!(stats.probes, LATENCY_WINDOW as u64 + 1);
+    }
+
+    #[test]
+    fn test_standing_order_budget() {
+        let mut order = StandingOrder {
+            id: 1,
+            tag: "science".to_string(),
+            max_price: 100,
+            min_reputation: None,
+            budget: None,
+            spent: 0,
+            created_at: 0,
+        };
+        assert_eq!(order.remaining_budget(), None);
+        assert!(order.can_afford(100));
+        assert!(!order.can_afford(101));
+
+        order.budget = Some(250);
+        order.spent = 200;
+        assert_eq!(order.remaining_budget(), Some(50));
+        assert!(order.can_afford(50));
+        assert!(!order.can_afford(60));
+
+        order.spent = 300;
+        assert_eq!(order.remaining_budget(), Some(0));
+        assert!(order.can_afford(0));
+    }
 }
@@ -0,0 +1,390 @@
+//! Vault management.
+//!
+//! A vault is an independent knowledge base with its own directory,
+//! graph database, and (optionally) its own protocol [`NodeState`].
+//! The [`VaultManager`] keeps a persisted index of vaults under a root
+//! directory so an application can create, open, close, and switch
+//! between vaults without restarting.
+//!
+//! # Layout
+//!
+//! ```text
+//! {root}/
+//! ├── vaults.json              # Vault index + per-vault settings
+//! └── vaults/
+//!     └── {name}/
+//!         ├── graph.db         # Knowledge graph database
+//!         ├── nodalync.db      # Protocol state (when enabled)
+//!         ├── content/
+//!         ├── cache/
+//!         └── identity/
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use nodalync_crypto::{current_timestamp, Timestamp};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, StoreError};
+use crate::{NodeState, NodeStateConfig};
+
+/// Name of the vault index file under the root directory.
+const VAULT_INDEX_FILE: &str = "vaults.json";
+
+/// Maximum length of a vault name.
+const MAX_VAULT_NAME_LEN: usize = 64;
+
+/// Per-vault settings, persisted in the vault index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct VaultSettings {
+    /// Whether the vault participates in the protocol (opens a `NodeState`).
+    pub protocol_enabled: bool,
+    /// Optional human-readable description.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Free-form application settings.
+    #[serde(default)]
+    pub extra: BTreeMap<String, String>,
+}
+
+impl Default for VaultSettings {
+    fn default() -> Self {
+        Self {
+            protocol_enabled: true,
+            description: None,
+            extra: BTreeMap::new(),
+        }
+    }
+}
+
+impl VaultSettings {
+    /// Enable or disable protocol state for the vault.
+    pub fn with_protocol(mut self, enabled: bool) -> Self {
+        self.protocol_enabled = enabled;
+        self
+    }
+
+    /// Set the vault description.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+/// Information about a registered vault.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct VaultInfo {
+    /// Unique vault name.
+    pub name: String,
+    /// Vault directory.
+    pub path: PathBuf,
+    /// When the vault was created.
+    pub created_at: Timestamp,
+    /// Vault settings.
+    pub settings: VaultSettings,
+}
+
+impl VaultInfo {
+    /// Path to the vault's knowledge graph database.
+    pub fn graph_db_path(&self) -> PathBuf {
+        self.path.join("graph.db")
+    }
+
+    /// Node state configuration rooted at the vault directory.
+    pub fn node_state_config(&self) -> NodeStateConfig {
+        NodeStateConfig::new(&self.path)
+    }
+}
+
+/// Persisted vault index.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct VaultIndex {
+    /// All registered vaults.
+    vaults: Vec<VaultInfo>,
+    /// Name of the most recently opened vault.
+    active: Option<String>,
+}
+
+/// Manager for multiple vaults under a single root directory.
+///
+/// Open vaults keep their `NodeState` (if protocol is enabled) in memory
+/// until closed.
+pub struct VaultManager {
+    /// Root directory holding the index and vault directories.
+    root: PathBuf,
+    /// Persisted index.
+    index: VaultIndex,
+    /// Currently open vaults and their protocol state.
+    open: HashMap<String, Option<NodeState>>,
+}
+
+impl VaultManager {
+    /// Load (or initialize) the vault index under `root`.
+    pub fn load(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(&root)?;
+
+        let index_path = root.join(VAULT_INDEX_FILE);
+        let index = if index_path.exists() {
+            let data = fs::read_to_string(&index_path)?;
+            serde_json::from_str(&data)?
+        } else {
+            VaultIndex::default()
+        };
+
+        Ok(Self {
+            root,
+            index,
+            open: HashMap::new(),
+        })
+    }
+
+    /// Get the root directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// List all registered vaults.
+    pub fn list(&self) -> &[VaultInfo] {
+        &self.index.vaults
+    }
+
+    /// Get a vault by name.
+    pub fn get(&self, name: &str) -> Option<&VaultInfo> {
+        self.index.vaults.iter().find(|v| v.name == name)
+    }
+
+    /// Get the most recently opened vault.
+    pub fn active(&self) -> Option<&VaultInfo> {
+        self.index.active.as_deref().and_then(|name| self.get(name))
+    }
+
+    /// Check if a vault is currently open.
+    pub fn is_open(&self, name: &str) -> bool {
+        self.open.contains_key(name)
+    }
+
+    /// Create a new vault.
+    ///
+    /// Returns an error if the name is invalid or already taken.
+    pub fn create(&mut self, name: &str, settings: VaultSettings) -> Result<VaultInfo> {
+        validate_vault_name(name)?;
+        if self.get(name).is_some() {
+            return Err(StoreError::invalid_data(format!(
+                "vault already exists: {}",
+                name
+            )));
+        }
+
+        let path = self.root.join("vaults").join(name);
+        fs::create_dir_all(&path)?;
+
+        let info = VaultInfo {
+            name: name.to_string(),
+            path,
+            created_at: current_timestamp(),
+            settings,
+        };
+        self.index.vaults.push(info.clone());
+        self.save()?;
+
+        tracing::info!(vault = %name, "Created vault");
+        Ok(info)
+    }
+
+    /// Open a vault and make it the active vault.
+    ///
+    /// Opens the vault's `NodeState` when protocol is enabled. Opening an
+    /// already-open vault only switches the active vault.
+    pub fn open_vault(&mut self, name: &str) -> Result<&VaultInfo> {
+        let info = self
+            .get(name)
+            .cloned()
+            .ok_or_else(|| StoreError::invalid_data(format!("vault not found: {}", name)))?;
+
+        if !self.open.contains_key(name) {
+            let state = if info.settings.protocol_enabled {
+                Some(NodeState::open(info.node_state_config())?)
+            } else {
+                None
+            };
+            self.open.insert(name.to_string(), state);
+            tracing::info!(vault = %name, "Opened vault");
+        }
+
+        self.index.active = Some(name.to_string());
+        self.save()?;
+
+        Ok(self.get(name).expect("vault exists"))
+    }
+
+    /// Close a vault, dropping its protocol state.
+    ///
+    /// Closing a vault that is not open is a no-op.
+    pub fn close_vault(&mut self, name: &str) -> Result<()> {
+        if self.open.remove(name).is_some() {
+            tracing::info!(vault = %name, "Closed vault");
+        }
+        Ok(())
+    }
+
+    /// Get the protocol state of an open vault.
+    ///
+    /// Returns `None` if the vault is not open or has protocol disabled.
+    pub fn state(&self, name: &str) -> Option<&NodeState> {
+        self.open.get(name).and_then(|s| s.as_ref())
+    }
+
+    /// Get mutable protocol state of an open vault.
+    pub fn state_mut(&mut self, name: &str) -> Option<&mut NodeState> {
+        self.open.get_mut(name).and_then(|s| s.as_mut())
+    }
+
+    /// Replace a vault's settings.
+    ///
+    /// Changes to `protocol_enabled` take effect the next time the vault is opened.
+    pub fn update_settings(&mut self, name: &str, settings: VaultSettings) -> Result<()> {
+        let vault = self
+            .index
+            .vaults
+            .iter_mut()
+            .find(|v| v.name == name)
+            .ok_or_else(|| StoreError::invalid_data(format!("vault not found: {}", name)))?;
+        vault.settings = settings;
+        self.save()
+    }
+
+    /// Write the vault index to disk.
+    fn save(&self) -> Result<()> {
+        let data = serde_json::to_string_pretty(&self.index)?;
+        let path = self.root.join(VAULT_INDEX_FILE);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, data)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
+
+/// Validate a vault name (used as a directory name).
+fn validate_vault_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_VAULT_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(StoreError::invalid_data(format!(
+            "invalid vault name '{}': use 1-{} letters, digits, '-' or '_'",
+            name, MAX_VAULT_NAME_LEN
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ContentStore;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_create_and_list_vaults() {
+        let temp = TempDir::new().unwrap();
+        let mut manager = VaultManager::load(temp.path()).unwrap();
+
+        manager
+            .create("research", VaultSettings::default())
+            .unwrap();
+        manager
+            .create("notes", VaultSettings::default().with_protocol(false))
+            .unwrap();
+
+        let names: Vec<_> = manager.list().iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, vec!["research", "notes"]);
+        assert!(manager.get("research").unwrap().path.exists());
+    }
+
+    #[test]
+    fn test_create_rejects_duplicates_and_bad_names() {
+        let temp = TempDir::new().unwrap();
+        let mut manager = VaultManager::load(temp.path()).unwrap();
+
+        manager.create("main", VaultSettings::default()).unwrap();
+        assert!(manager.create("main", VaultSettings::default()).is_err());
+        assert!(manager.create("", VaultSettings::default()).is_err());
+        assert!(manager
+            .create("../escape", VaultSettings::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_open_close_vault_state() {
+        let temp = TempDir::new().unwrap();
+        let mut manager = VaultManager::load(temp.path()).unwrap();
+        manager.create("a", VaultSettings::default()).unwrap();
+        manager
+            .create("b", VaultSettings::default().with_protocol(false))
+            .unwrap();
+
+        manager.open_vault("a").unwrap();
+        manager.open_vault("b").unwrap();
+        assert!(manager.is_open("a"));
+        assert!(manager.state("a").is_some());
+        assert!(manager.state("b").is_none());
+        assert_eq!(manager.active().unwrap().name, "b");
+
+        let hash = manager
+            .state_mut("a")
+            .unwrap()
+            .content
+            .store(b"vault content")
+            .unwrap();
+        assert!(manager.state("a").unwrap().content.exists(&hash));
+
+        manager.close_vault("a").unwrap();
+        assert!(!manager.is_open("a"));
+        assert!(manager.state("a").is_none());
+    }
+
+    #[test]
+    fn test_vault_index_persisted() {
+        let temp = TempDir::new().unwrap();
+        {
+            let mut manager = VaultManager::load(temp.path()).unwrap();
+            manager
+                .create(
+                    "persisted",
+                    VaultSettings::default().with_description("My vault"),
+                )
+                .unwrap();
+            manager.open_vault("persisted").unwrap();
+        }
+
+        let manager = VaultManager::load(temp.path()).unwrap();
+        let vault = manager.get("persisted").unwrap();
+        assert_eq!(vault.settings.description.as_deref(), Some("My vault"));
+        assert_eq!(manager.active().unwrap().name, "persisted");
+        assert!(!manager.is_open("persisted"));
+    }
+
+    #[test]
+    fn test_update_settings() {
+        let temp = TempDir::new().unwrap();
+        let mut manager = VaultManager::load(temp.path()).unwrap();
+        manager.create("v", VaultSettings::default()).unwrap();
+
+        let mut settings = VaultSettings::default().with_protocol(false);
+        settings.extra.insert("theme".into(), "dark".into());
+        manager.update_settings("v", settings.clone()).unwrap();
+
+        assert_eq!(manager.get("v").unwrap().settings, settings);
+        assert!(manager
+            .update_settings("missing", VaultSettings::default())
+            .is_err());
+    }
+}
@@ -0,0 +1,323 @@
+//! Webhook delivery storage.
+//!
+//! Each notification is queued once per endpoint and retried until the
+//! endpoint accepts it or the attempts run out. Finished deliveries are
+//! kept as a record of what was sent until they are pruned.
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::sync::{Arc, Mutex};
+
+use nodalync_crypto::Timestamp;
+
+use crate::error::{Result, StoreError};
+use crate::traits::WebhookStore;
+use crate::types::{WebhookDelivery, WebhookDeliveryStatus};
+
+/// Columns selected for a [`WebhookDelivery`], in `row_to_delivery` order.
+const DELIVERY_COLUMNS: &str = "id, url, event, payload, status, attempts, next_attempt_at, \
+                                last_error, created_at, finished_at";
+
+/// SQLite-based webhook delivery store.
+pub struct SqliteWebhookStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteWebhookStore {
+    /// Create a new webhook store with the given database connection.
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+}
+
+fn row_to_delivery(row: &Row<'_>) -> rusqlite::Result<WebhookDelivery> {
+    let status: String = row.get(4)?;
+    let status = WebhookDeliveryStatus::parse(&status).ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(
+            4,
+            rusqlite::types::Type::Text,
+            format!("unknown webhook delivery status: {}", status).into(),
+        )
+    })?;
+
+    Ok(WebhookDelivery {
+        id: row.get::<_, i64>(0)? as u64,
+        url: row.get(1)?,
+        event: row.get(2)?,
+        payload: row.get(3)?,
+        status,
+        attempts: row.get::<_, i64>(5)? as u32,
+        next_attempt_at: row.get::<_, i64>(6)? as Timestamp,
+        last_error: row.get(7)?,
+        created_at: row.get::<_, i64>(8)? as Timestamp,
+        finished_at: row.get::<_, Option<i64>>(9)?.map(|t| t as Timestamp),
+    })
+}
+
+impl WebhookStore for SqliteWebhookStore {
+    fn enqueue(&self, url: &str, event: &str, payload: &str, created_at: Timestamp) -> Result<u64> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        conn.execute(
+            "INSERT INTO webhook_deliveries
+             (url, event, payload, status, attempts, next_attempt_at, created_at)
+             VALUES (?1, ?2, ?3, ?4, 0, ?5, ?5)",
+            params![
+                url,
+                event,
+                payload,
+                WebhookDeliveryStatus::Pending.as_str(),
+                created_at as i64
+            ],
+        )?;
+
+        Ok(conn.last_insert_rowid() as u64)
+    }
+
+    fn get(&self, id: u64) -> Result<Option<WebhookDelivery>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let delivery = conn
+            .prepare_cached(&format!(
+                "SELECT {} FROM webhook_deliveries WHERE id = ?1",
+                DELIVERY_COLUMNS
+            ))?
+            .query_row([id as i64], row_to_delivery)
+            .optional()?;
+
+        Ok(delivery)
+    }
+
+    fn due(&self, now: Timestamp, limit: u32) -> Result<Vec<WebhookDelivery>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM webhook_deliveries
+             WHERE status = ?1 AND next_attempt_at <= ?2
+             ORDER BY next_attempt_at ASC, id ASC
+             LIMIT ?3",
+            DELIVERY_COLUMNS
+        ))?;
+        let deliveries = stmt
+            .query_map(
+                params![
+                    WebhookDeliveryStatus::Pending.as_str(),
+                    now as i64,
+                    limit as i64
+                ],
+                row_to_delivery,
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(deliveries)
+    }
+
+    fn mark_delivered(&self, id: u64, at: Timestamp) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let updated = conn.execute(
+            "UPDATE webhook_deliveries
+             SET status = ?2, attempts = attempts + 1, last_error = NULL, finished_at = ?3
+             WHERE id = ?1",
+            params![
+                id as i64,
+                WebhookDeliveryStatus::Delivered.as_str(),
+                at as i64
+            ],
+        )?;
+        if updated == 0 {
+            return Err(StoreError::WebhookDeliveryNotFound(id));
+        }
+
+        Ok(())
+    }
+
+    fn record_failure(
+        &self,
+        id: u64,
+        error: &str,
+        at: Timestamp,
+        retry_at: Option<Timestamp>,
+    ) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let updated = match retry_at {
+            Some(retry_at) => conn.execute(
+                "UPDATE webhook_deliveries
+                 SET attempts = attempts + 1, last_error = ?2, next_attempt_at = ?3
+                 WHERE id = ?1",
+                params![id as i64, error, retry_at as i64],
+            )?,
+            None => conn.execute(
+                "UPDATE webhook_deliveries
+                 SET status = ?2, attempts = attempts + 1, last_error = ?3, finished_at = ?4
+                 WHERE id = ?1",
+                params![
+                    id as i64,
+                    WebhookDeliveryStatus::Failed.as_str(),
+                    error,
+                    at as i64
+                ],
+            )?,
+        };
+        if updated == 0 {
+            return Err(StoreError::WebhookDeliveryNotFound(id));
+        }
+
+        Ok(())
+    }
+
+    fn list(
+        &self,
+        status: Option<WebhookDeliveryStatus>,
+        limit: u32,
+    ) -> Result<Vec<WebhookDelivery>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM webhook_deliveries
+             WHERE ?1 IS NULL OR status = ?1
+             ORDER BY created_at DESC, id DESC
+             LIMIT ?2",
+            DELIVERY_COLUMNS
+        ))?;
+        let deliveries = stmt
+            .query_map(
+                params![status.map(|s| s.as_str()), limit as i64],
+                row_to_delivery,
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(deliveries)
+    }
+
+    fn prune(&self, before: Timestamp) -> Result<usize> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StoreError::lock_poisoned("database connection lock poisoned"))?;
+
+        let deleted = conn.execute(
+            "DELETE FROM webhook_deliveries
+             WHERE status != ?1 AND finished_at < ?2",
+            params![WebhookDeliveryStatus::Pending.as_str(), before as i64],
+        )?;
+
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::initialize_schema;
+
+    fn setup_store() -> SqliteWebhookStore {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        SqliteWebhookStore::new(Arc::new(Mutex::new(conn)))
+    }
+
+    #[test]
+    fn test_enqueue_and_deliver() {
+        let store = setup_store();
+        let id = store
+            .enqueue("https://example.com/hook", "payment_received", "{}", 1_000)
+            .unwrap();
+
+        let delivery = store.get(id).unwrap().unwrap();
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Pending);
+        assert_eq!(delivery.attempts, 0);
+        assert_eq!(delivery.next_attempt_at, 1_000);
+        assert_eq!(store.due(999, 10).unwrap(), vec![]);
+        assert_eq!(store.due(1_000, 10).unwrap(), vec![delivery]);
+
+        store.mark_delivered(id, 1_500).unwrap();
+        let delivery = store.get(id).unwrap().unwrap();
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Delivered);
+        assert_eq!(delivery.attempts, 1);
+        assert_eq!(delivery.finished_at, Some(1_500));
+        assert!(store.due(2_000, 10).unwrap().is_empty());
+
+        assert!(matches!(
+            store.mark_delivered(id + 1, 1_500),
+            Err(StoreError::WebhookDeliveryNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_failures_retry_then_give_up() {
+        let store = setup_store();
+        let id = store
+            .enqueue("https://example.com/hook", "channel_disputed", "{}", 1_000)
+            .unwrap();
+
+        store
+            .record_failure(id, "connection refused", 1_000, Some(3_000))
+            .unwrap();
+        let delivery = store.get(id).unwrap().unwrap();
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Pending);
+        assert_eq!(delivery.attempts, 1);
+        assert_eq!(delivery.last_error.as_deref(), Some("connection refused"));
+        assert!(store.due(2_000, 10).unwrap().is_empty());
+        assert_eq!(store.due(3_000, 10).unwrap().len(), 1);
+
+        store.record_failure(id, "HTTP 500", 3_000, None).unwrap();
+        let delivery = store.get(id).unwrap().unwrap();
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Failed);
+        assert_eq!(delivery.attempts, 2);
+        assert_eq!(delivery.finished_at, Some(3_000));
+        assert!(store.due(10_000, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_list_and_prune() {
+        let store = setup_store();
+        let delivered = store
+            .enqueue("https://a.example", "e", "{}", 1_000)
+            .unwrap();
+        let failed = store
+            .enqueue("https://b.example", "e", "{}", 2_000)
+            .unwrap();
+        let pending = store
+            .enqueue("https://c.example", "e", "{}", 3_000)
+            .unwrap();
+        store.mark_delivered(delivered, 1_100).unwrap();
+        store
+            .record_failure(failed, "timeout", 2_100, None)
+            .unwrap();
+
+        let ids: Vec<u64> = store.list(None, 10).unwrap().iter().map(|d| d.id).collect();
+        assert_eq!(ids, vec![pending, failed, delivered]);
+        let ids: Vec<u64> = store
+            .list(Some(WebhookDeliveryStatus::Failed), 10)
+            .unwrap()
+            .iter()
+            .map(|d| d.id)
+            .collect();
+        assert_eq!(ids, vec![failed]);
+        assert_eq!(store.list(None, 1).unwrap().len(), 1);
+
+        // Pending deliveries are never pruned
+        assert_eq!(store.prune(2_000).unwrap(), 1);
+        assert_eq!(store.prune(10_000).unwrap(), 1);
+        assert!(store.get(pending).unwrap().is_some());
+    }
+}
@@ -0,0 +1,301 @@
+//! Verifiable attribution certificates for derived content.
+//!
+//! An attribution certificate is a portable proof of where an L3 item came
+//! from: its provenance chain with the weight of every root source, the
+//! on-chain attestations that anchor the chain, and signatures from the
+//! owners involved. It serializes as JSON-LD against the Nodalync ontology
+//! so it can travel with model outputs built from the content.
+//!
+//! The certificate hash covers every term except the proofs. The L3 owner
+//! signs it when issuing the certificate; owners of root sources may add
+//! their own signatures to endorse the attribution. Signers are named by
+//! `did:key` DID URLs, so a certificate verifies without a key lookup.
+
+use nodalync_crypto::{
+    content_hash, did_from_public_key, peer_id_from_public_key, Hash, PeerId, PublicKey, Signature,
+    Timestamp,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::provenance::Provenance;
+
+/// JSON-LD vocabulary of attribution certificates
+pub const ATTRIBUTION_VOCAB: &str = "https://nodalync.io/ontology/";
+
+/// JSON-LD type of attribution certificates
+pub const ATTRIBUTION_CERTIFICATE_TYPE: &str = "AttributionCertificate";
+
+/// JSON-LD context of an attribution certificate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttributionContext {
+    /// Vocabulary the certificate's terms expand against
+    #[serde(rename = "@vocab")]
+    pub vocab: String,
+}
+
+impl Default for AttributionContext {
+    fn default() -> Self {
+        Self {
+            vocab: ATTRIBUTION_VOCAB.to_string(),
+        }
+    }
+}
+
+/// A root source of the attributed content and its weight.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceAttribution {
+    /// Root L0/L1 content hash
+    pub content_hash: Hash,
+    /// Owner of the source
+    pub owner: PeerId,
+    /// Weight of the source in the provenance chain
+    pub weight: u32,
+}
+
+/// Reference to an on-chain content attestation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationReference {
+    /// Attested content hash
+    pub content_hash: Hash,
+    /// Provenance root recorded on-chain
+    pub provenance_root: Hash,
+    /// Settlement account that made the attestation
+    pub account: String,
+    /// When the attestation was made
+    pub timestamp: Timestamp,
+}
+
+/// An owner's signature over a certificate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributionProof {
+    /// DID URL of the signing key (`did:key:z6Mk...#z6Mk...`)
+    pub verification_method: String,
+    /// When the signature was made
+    pub created: Timestamp,
+    /// Signature over the certificate hash
+    pub signature: Signature,
+}
+
+/// A signed, portable attribution certificate for an L3 item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributionCertificate {
+    /// JSON-LD context
+    #[serde(rename = "@context")]
+    pub context: AttributionContext,
+    /// JSON-LD type (`AttributionCertificate`)
+    #[serde(rename = "@type")]
+    pub certificate_type: String,
+    /// Hash of the certificate terms (see [`AttributionCertificate::compute_hash`])
+    pub hash: Hash,
+    /// The attributed L3 content
+    pub content_hash: Hash,
+    /// Title of the attributed content
+    pub title: String,
+    /// Owner of the attributed content
+    pub owner: PeerId,
+    /// `did:key` DID of the owner
+    pub owner_did: String,
+    /// Direct sources of the content
+    pub derived_from: Vec<Hash>,
+    /// Derivation depth from the furthest L0
+    pub depth: u32,
+    /// Root sources with their weights
+    pub sources: Vec<SourceAttribution>,
+    /// On-chain attestations of the content and its sources
+    #[serde(default)]
+    pub attestations: Vec<AttestationReference>,
+    /// When the certificate was issued
+    pub issued_at: Timestamp,
+    /// Owner signatures over `hash`; the first is the issuer's
+    #[serde(default)]
+    pub proofs: Vec<AttributionProof>,
+}
+
+impl AttributionCertificate {
+    /// Create an unsigned certificate for content owned by `owner_key`.
+    ///
+    /// The hash is computed from the terms; proofs are added as owners
+    /// sign.
+    pub fn new(
+        content_hash: Hash,
+        title: impl Into<String>,
+        owner_key: &PublicKey,
+        provenance: &Provenance,
+        attestations: Vec<AttestationReference>,
+        issued_at: Timestamp,
+    ) -> Self {
+        let mut certificate = Self {
+            context: AttributionContext::default(),
+            certificate_type: ATTRIBUTION_CERTIFICATE_TYPE.to_string(),
+            hash: Hash([0u8; 32]),
+            content_hash,
+            title: title.into(),
+            owner: peer_id_from_public_key(owner_key),
+            owner_did: did_from_public_key(owner_key),
+            derived_from: provenance.derived_from.clone(),
+            depth: provenance.depth,
+            sources: provenance
+                .root_l0l1
+                .iter()
+                .map(|entry| SourceAttribution {
+                    content_hash: entry.hash,
+                    owner: entry.owner,
+                    weight: entry.weight,
+                })
+                .collect(),
+            attestations,
+            issued_at,
+            proofs: Vec::new(),
+        };
+        certificate.hash = certificate.compute_hash();
+        certificate
+    }
+
+    /// Compute the hash of the certificate terms.
+    ///
+    /// Covers every field except the JSON-LD annotations, `hash` and
+    /// `proofs`. Strings and lists are length-prefixed.
+    pub fn compute_hash(&self) -> Hash {
+        let mut data = Vec::with_capacity(256 + 56 * self.sources.len());
+        data.extend_from_slice(&self.content_hash.0);
+        push_str(&mut data, &self.title);
+        data.extend_from_slice(&self.owner.0);
+        push_str(&mut data, &self.owner_did);
+
+        data.extend_from_slice(&(self.derived_from.len() as u32).to_be_bytes());
+        for hash in &self.derived_from {
+            data.extend_from_slice(&hash.0);
+        }
+        data.extend_from_slice(&self.depth.to_be_bytes());
+
+        data.extend_from_slice(&(self.sources.len() as u32).to_be_bytes());
+        for source in &self.sources {
+            data.extend_from_slice(&source.content_hash.0);
+            data.extend_from_slice(&source.owner.0);
+            data.extend_from_slice(&source.weight.to_be_bytes());
+        }
+
+        data.extend_from_slice(&(self.attestations.len() as u32).to_be_bytes());
+        for attestation in &self.attestations {
+            data.extend_from_slice(&attestation.content_hash.0);
+            data.extend_from_slice(&attestation.provenance_root.0);
+            push_str(&mut data, &attestation.account);
+            data.extend_from_slice(&attestation.timestamp.to_be_bytes());
+        }
+
+        data.extend_from_slice(&self.issued_at.to_be_bytes());
+        content_hash(&data)
+    }
+
+    /// Get the share of each root owner, by total weight.
+    ///
+    /// Owners appear once, in order of first appearance.
+    pub fn owner_weights(&self) -> Vec<(PeerId, u32)> {
+        let mut weights: Vec<(PeerId, u32)> = Vec::new();
+        for source in &self.sources {
+            match weights.iter_mut().find(|(owner, _)| *owner == source.owner) {
+                Some((_, weight)) => *weight = weight.saturating_add(source.weight),
+                None => weights.push((source.owner, source.weight)),
+            }
+        }
+        weights
+    }
+}
+
+fn push_str(data: &mut Vec<u8>, s: &str) {
+    data.extend_from_slice(&(s.len() as u32).to_be_bytes());
+    data.extend_from_slice(s.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::Visibility;
+    use crate::provenance::ProvenanceEntry;
+    use nodalync_crypto::generate_identity;
+
+    fn test_certificate() -> AttributionCertificate {
+        let (_, owner_key) = generate_identity();
+        let (_, source_key) = generate_identity();
+        let source_owner = peer_id_from_public_key(&source_key);
+        let a = content_hash(b"source a");
+        let b = content_hash(b"source b");
+        let provenance = Provenance::new_derived(
+            vec![
+                ProvenanceEntry::with_weight(a, source_owner, Visibility::Shared, 2),
+                ProvenanceEntry::new(b, peer_id_from_public_key(&owner_key), Visibility::Shared),
+            ],
+            vec![a, b],
+            1,
+        );
+        AttributionCertificate::new(
+            content_hash(b"insight"),
+            "Insight",
+            &owner_key,
+            &provenance,
+            vec![],
+            1_000,
+        )
+    }
+
+    #[test]
+    fn test_certificate_hash_covers_terms() {
+        let certificate = test_certificate();
+        assert_eq!(certificate.hash, certificate.compute_hash());
+        assert_eq!(certificate.sources.len(), 2);
+        assert_eq!(certificate.sources[0].weight, 2);
+
+        let mut changed = certificate.clone();
+        changed.sources[0].weight = 3;
+        assert_ne!(changed.compute_hash(), certificate.hash);
+
+        let mut changed = certificate.clone();
+        changed.attestations.push(AttestationReference {
+            content_hash: certificate.content_hash,
+            provenance_root: certificate.sources[0].content_hash,
+            account: "0.0.1234".to_string(),
+            timestamp: 500,
+        });
+        assert_ne!(changed.compute_hash(), certificate.hash);
+
+        // Proofs are not part of the terms
+        let mut signed = certificate.clone();
+        signed.proofs.push(AttributionProof {
+            verification_method: certificate.owner_did.clone(),
+            created: 1_000,
+            signature: Signature::from_bytes([0u8; 64]),
+        });
+        assert_eq!(signed.compute_hash(), certificate.hash);
+    }
+
+    #[test]
+    fn test_certificate_json_ld() {
+        let certificate = test_certificate();
+        let json: serde_json::Value = serde_json::to_value(&certificate).unwrap();
+        assert_eq!(json["@context"]["@vocab"], ATTRIBUTION_VOCAB);
+        assert_eq!(json["@type"], ATTRIBUTION_CERTIFICATE_TYPE);
+        assert_eq!(json["ownerDid"], certificate.owner_did.as_str());
+        assert_eq!(json["sources"][0]["weight"], 2);
+
+        let parsed: AttributionCertificate = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, certificate);
+    }
+
+    #[test]
+    fn test_owner_weights() {
+        let mut certificate = test_certificate();
+        let first = certificate.sources[0].clone();
+        certificate.sources.push(SourceAttribution {
+            content_hash: content_hash(b"source c"),
+            ..first.clone()
+        });
+        let weights = certificate.owner_weights();
+        assert_eq!(weights.len(), 2);
+        assert_eq!(weights[0], (first.owner, 4));
+        assert_eq!(weights[1].1, 1);
+    }
+}
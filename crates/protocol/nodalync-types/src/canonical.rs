@@ -0,0 +1,118 @@
+//! Signed pointers choosing the canonical version among forks.
+//!
+//! Nothing stops an owner publishing two different versions with the same
+//! number in one lineage. Peers holding both flag them as forked and pick
+//! one by their resolution policy; under the owner-canonical policy, the
+//! owner's signed pointer decides. Later pointers for a root supersede
+//! earlier ones. Like tombstones, the pointer carries the owner's public
+//! key so it can be verified without a key lookup.
+
+use nodalync_crypto::{content_hash, Hash, PeerId, PublicKey, Signature, Timestamp};
+use serde::{Deserialize, Serialize};
+
+/// An owner's signed choice of the canonical version of a lineage.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CanonicalVersion {
+    /// Hash of the pointer terms (see [`CanonicalVersion::compute_hash`])
+    pub hash: Hash,
+    /// Version root of the lineage
+    pub root: Hash,
+    /// The version chosen as canonical
+    pub version: Hash,
+    /// Content owner choosing it
+    pub owner: PeerId,
+    /// Owner's public key, for signature verification
+    pub owner_key: PublicKey,
+    /// When the choice was made
+    pub created_at: Timestamp,
+    /// Owner's signature over `hash`
+    pub signature: Signature,
+}
+
+impl CanonicalVersion {
+    /// Create an unsigned pointer.
+    ///
+    /// The hash is computed from the terms; the signature is left zeroed
+    /// until the owner signs it.
+    pub fn new(
+        root: Hash,
+        version: Hash,
+        owner: PeerId,
+        owner_key: PublicKey,
+        created_at: Timestamp,
+    ) -> Self {
+        let mut pointer = Self {
+            hash: Hash([0u8; 32]),
+            root,
+            version,
+            owner,
+            owner_key,
+            created_at,
+            signature: Signature::from_bytes([0u8; 64]),
+        };
+        pointer.hash = pointer.compute_hash();
+        pointer
+    }
+
+    /// Compute the hash of the pointer terms.
+    ///
+    /// Covers every field except `hash` and `signature`.
+    pub fn compute_hash(&self) -> Hash {
+        let mut data = Vec::with_capacity(128);
+        data.extend_from_slice(&self.root.0);
+        data.extend_from_slice(&self.version.0);
+        data.extend_from_slice(&self.owner.0);
+        data.extend_from_slice(&self.owner_key.0);
+        data.extend_from_slice(&self.created_at.to_be_bytes());
+        content_hash(&data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+
+    fn test_pointer() -> CanonicalVersion {
+        let (_, public_key) = generate_identity();
+        CanonicalVersion::new(
+            content_hash(b"v1"),
+            content_hash(b"v3"),
+            peer_id_from_public_key(&public_key),
+            public_key,
+            1_000,
+        )
+    }
+
+    #[test]
+    fn test_canonical_version_hash_covers_terms() {
+        let pointer = test_pointer();
+        assert_eq!(pointer.hash, pointer.compute_hash());
+
+        let mut changed = pointer.clone();
+        changed.version = content_hash(b"other v3");
+        assert_ne!(changed.compute_hash(), pointer.hash);
+
+        let mut changed = pointer.clone();
+        changed.root = content_hash(b"other v1");
+        assert_ne!(changed.compute_hash(), pointer.hash);
+
+        let mut changed = pointer.clone();
+        changed.created_at += 1;
+        assert_ne!(changed.compute_hash(), pointer.hash);
+
+        // The signature is not part of the terms
+        let mut signed = pointer.clone();
+        signed.signature = Signature::from_bytes([1u8; 64]);
+        assert_eq!(signed.compute_hash(), pointer.hash);
+    }
+
+    #[test]
+    fn test_canonical_version_serialization() {
+        let pointer = test_pointer();
+        let json = serde_json::to_string(&pointer).unwrap();
+        let parsed: CanonicalVersion = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, pointer);
+    }
+}
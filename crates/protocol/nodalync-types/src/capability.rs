@@ -0,0 +1,148 @@
+//! Capability tokens for sharing unpublished content.
+//!
+//! A capability token lets the owner of a piece of content share it with
+//! someone without publishing it. The owner signs a token naming the
+//! content hash, an expiry and optionally the one peer allowed to use it;
+//! the requester attaches the token to its query. Like invoices, the
+//! token carries the issuer's public key so it can be verified without a
+//! key lookup.
+
+use nodalync_crypto::{content_hash, Hash, PeerId, PublicKey, Signature, Timestamp};
+use serde::{Deserialize, Serialize};
+
+/// A signed, expiring grant of access to one piece of content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CapabilityToken {
+    /// Hash of the token terms (see [`CapabilityToken::compute_hash`])
+    pub hash: Hash,
+    /// Content the token grants access to
+    pub content_hash: Hash,
+    /// Content owner who issued the token
+    pub issuer: PeerId,
+    /// Issuer's public key, for signature verification
+    pub issuer_key: PublicKey,
+    /// Only peer allowed to use the token (anyone holding it if `None`)
+    pub grantee: Option<PeerId>,
+    /// When the token was issued
+    pub issued_at: Timestamp,
+    /// When the token stops granting access
+    pub expires_at: Timestamp,
+    /// Issuer's signature over `hash`
+    pub signature: Signature,
+}
+
+impl CapabilityToken {
+    /// Create an unsigned token.
+    ///
+    /// The hash is computed from the terms; the signature is left zeroed
+    /// until the issuer signs it.
+    pub fn new(
+        content_hash: Hash,
+        issuer: PeerId,
+        issuer_key: PublicKey,
+        grantee: Option<PeerId>,
+        issued_at: Timestamp,
+        expires_at: Timestamp,
+    ) -> Self {
+        let mut token = Self {
+            hash: Hash([0u8; 32]),
+            content_hash,
+            issuer,
+            issuer_key,
+            grantee,
+            issued_at,
+            expires_at,
+            signature: Signature::from_bytes([0u8; 64]),
+        };
+        token.hash = token.compute_hash();
+        token
+    }
+
+    /// Compute the hash of the token terms.
+    ///
+    /// Covers every field except `hash` and `signature`.
+    pub fn compute_hash(&self) -> Hash {
+        let mut data = Vec::with_capacity(128);
+        data.extend_from_slice(&self.content_hash.0);
+        data.extend_from_slice(&self.issuer.0);
+        data.extend_from_slice(&self.issuer_key.0);
+        match &self.grantee {
+            Some(grantee) => {
+                data.push(1);
+                data.extend_from_slice(&grantee.0);
+            }
+            None => data.push(0),
+        }
+        data.extend_from_slice(&self.issued_at.to_be_bytes());
+        data.extend_from_slice(&self.expires_at.to_be_bytes());
+        content_hash(&data)
+    }
+
+    /// Check if the token has expired at the given time.
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Check if the token may be used by a peer.
+    pub fn allows(&self, peer: &PeerId) -> bool {
+        self.grantee.is_none_or(|grantee| grantee == *peer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+
+    fn test_token(grantee: Option<PeerId>) -> CapabilityToken {
+        let (_, public_key) = generate_identity();
+        CapabilityToken::new(
+            content_hash(b"private notes"),
+            peer_id_from_public_key(&public_key),
+            public_key,
+            grantee,
+            1_000,
+            2_000,
+        )
+    }
+
+    #[test]
+    fn test_capability_hash_covers_terms() {
+        let token = test_token(None);
+        assert_eq!(token.hash, token.compute_hash());
+
+        let mut changed = token.clone();
+        changed.content_hash = content_hash(b"other notes");
+        assert_ne!(changed.compute_hash(), token.hash);
+
+        let mut changed = token.clone();
+        changed.grantee = Some(PeerId([7u8; 20]));
+        assert_ne!(changed.compute_hash(), token.hash);
+
+        let mut changed = token.clone();
+        changed.expires_at += 1;
+        assert_ne!(changed.compute_hash(), token.hash);
+    }
+
+    #[test]
+    fn test_capability_expiry_and_grantee() {
+        let grantee = PeerId([7u8; 20]);
+        let token = test_token(Some(grantee));
+        assert!(!token.is_expired(1_999));
+        assert!(token.is_expired(2_000));
+        assert!(token.allows(&grantee));
+        assert!(!token.allows(&PeerId([8u8; 20])));
+
+        let bearer = test_token(None);
+        assert!(bearer.allows(&PeerId([8u8; 20])));
+    }
+
+    #[test]
+    fn test_capability_serialization() {
+        let token = test_token(Some(PeerId([7u8; 20])));
+        let json = serde_json::to_string(&token).unwrap();
+        let decoded: CapabilityToken = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, token);
+    }
+}
@@ -0,0 +1,140 @@
+//! Collection types.
+//!
+//! A collection is a curated, ordered list of existing content (a reading
+//! list or playlist). Its own content is the serialized `Collection`; its
+//! manifest has `ContentType::Collection` and provenance derived from the
+//! members, weighted by each item's `weight`. When a collection is sold as a
+//! bundle, revenue is distributed to the members' roots by those weights.
+
+use nodalync_crypto::Hash;
+use serde::{Deserialize, Serialize};
+
+use crate::Amount;
+
+/// One entry in a collection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CollectionItem {
+    /// Hash of the member content
+    pub hash: Hash,
+    /// Display title of the member
+    pub title: String,
+    /// Share of bundle revenue relative to the other items
+    pub weight: u32,
+}
+
+impl CollectionItem {
+    /// Create a new item with weight 1.
+    pub fn new(hash: Hash, title: impl Into<String>) -> Self {
+        Self {
+            hash,
+            title: title.into(),
+            weight: 1,
+        }
+    }
+
+    /// Set the item's weight.
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+/// A curated, ordered list of content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Collection {
+    /// Title of the collection
+    pub title: String,
+    /// Optional description
+    pub description: Option<String>,
+    /// Items in display order
+    pub items: Vec<CollectionItem>,
+    /// Price of the whole bundle; `None` means the list itself is free
+    pub bundle_price: Option<Amount>,
+}
+
+impl Collection {
+    /// Create an empty collection.
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            description: None,
+            items: Vec::new(),
+            bundle_price: None,
+        }
+    }
+
+    /// Set the description.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Append an item.
+    pub fn with_item(mut self, item: CollectionItem) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    /// Set the bundle price.
+    pub fn with_bundle_price(mut self, price: Amount) -> Self {
+        self.bundle_price = Some(price);
+        self
+    }
+
+    /// Price charged for the collection (0 if not sold as a bundle).
+    pub fn price(&self) -> Amount {
+        self.bundle_price.unwrap_or(0)
+    }
+
+    /// Sum of all item weights.
+    pub fn total_weight(&self) -> u64 {
+        self.items.iter().map(|i| i.weight as u64).sum()
+    }
+
+    /// Check whether a hash is a member of this collection.
+    pub fn contains(&self, hash: &Hash) -> bool {
+        self.items.iter().any(|i| &i.hash == hash)
+    }
+
+    /// Member hashes in order.
+    pub fn hashes(&self) -> Vec<Hash> {
+        self.items.iter().map(|i| i.hash).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::content_hash;
+
+    #[test]
+    fn test_collection_builder() {
+        let a = content_hash(b"a");
+        let b = content_hash(b"b");
+        let collection = Collection::new("Reading list")
+            .with_description("Papers worth reading")
+            .with_item(CollectionItem::new(a, "A"))
+            .with_item(CollectionItem::new(b, "B").with_weight(3))
+            .with_bundle_price(500);
+
+        assert_eq!(collection.items.len(), 2);
+        assert_eq!(collection.total_weight(), 4);
+        assert_eq!(collection.price(), 500);
+        assert!(collection.contains(&b));
+        assert!(!collection.contains(&content_hash(b"c")));
+        assert_eq!(collection.hashes(), vec![a, b]);
+        assert_eq!(Collection::new("Free").price(), 0);
+    }
+
+    #[test]
+    fn test_collection_serialization() {
+        let collection = Collection::new("Reading list")
+            .with_item(CollectionItem::new(content_hash(b"a"), "A").with_weight(2));
+
+        let json = serde_json::to_string(&collection).unwrap();
+        let deserialized: Collection = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, collection);
+    }
+}
@@ -25,6 +25,29 @@ pub const MAX_CONTENT_SIZE: u64 = 104_857_600;
 /// Maximum message size: 10 MB
 pub const MAX_MESSAGE_SIZE: u64 = 10_485_760;
 
+/// Chunk size for content downloaded in chunks: 1 MiB
+///
+/// Content larger than one chunk records a hash per chunk in its manifest.
+pub const CHUNK_SIZE: u64 = 1_048_576;
+
+/// Maximum chunk size a manifest may declare: 4 MiB
+pub const MAX_CHUNK_SIZE: u64 = 4_194_304;
+
+/// Maximum priced sections a manifest may declare
+pub const MAX_SECTIONS: usize = 100;
+
+/// Smallest unit metered content may be delivered in: 1 KiB
+pub const MIN_METERED_UNIT_SIZE: u64 = 1_024;
+
+/// Maximum units metered content may be split into
+pub const MAX_METERED_UNITS: usize = 1_000;
+
+/// Maximum free reads a trial policy may offer each requester
+pub const MAX_TRIAL_READS: u32 = 100;
+
+/// Length of a quota period: quotas reset at the start of each UTC day
+pub const QUOTA_PERIOD_MS: u64 = 86_400_000;
+
 /// Maximum mentions that can be extracted from a single L0
 pub const MAX_MENTIONS_PER_L0: u32 = 1000;
 
@@ -50,6 +73,21 @@ pub const MAX_TITLE_LENGTH: usize = 200;
 /// Maximum description length (characters)
 pub const MAX_DESCRIPTION_LENGTH: usize = 2000;
 
+/// Maximum metadata schema URI length (characters)
+pub const MAX_SCHEMA_URI_LENGTH: usize = 256;
+
+/// Maximum size of structured metadata fields (bytes of JSON)
+pub const MAX_METADATA_FIELDS_SIZE: usize = 16 * 1024;
+
+/// Maximum redaction patterns, and separately sections, in a preview policy
+pub const MAX_REDACTION_RULES: usize = 20;
+
+/// Maximum length of a redaction pattern or section heading (characters)
+pub const MAX_REDACTION_RULE_LENGTH: usize = 200;
+
+/// Text that replaces redacted matches in preview summaries
+pub const REDACTION_MARKER: &str = "[redacted]";
+
 /// Maximum summary length (characters)
 pub const MAX_SUMMARY_LENGTH: usize = 500;
 
@@ -81,6 +119,26 @@ pub const SYNTHESIS_FEE_NUMERATOR: u64 = 5;
 /// Synthesis fee denominator (100)
 pub const SYNTHESIS_FEE_DENOMINATOR: u64 = 100;
 
+/// Basis points in a whole (100%)
+pub const BASIS_POINTS_DENOMINATOR: u64 = 10_000;
+
+/// Maximum app-level platform fee: 10% (in basis points)
+pub const MAX_APP_FEE_BASIS_POINTS: u32 = 1_000;
+
+/// Maximum referral cut an owner can offer: 20% (in basis points)
+pub const MAX_REFERRAL_BASIS_POINTS: u32 = 2_000;
+
+/// How far from the current time a dynamic price is also accepted at:
+/// 1 minute (in milliseconds), covering the time from quote to payment
+pub const DYNAMIC_PRICE_GRACE_MS: u64 = 60_000;
+
+/// Shortest surge pricing window: 1 minute (in milliseconds)
+pub const MIN_SURGE_WINDOW_MS: u64 = 60_000;
+
+/// Largest surge pricing step: each paid query adds at most the base price
+/// (in basis points)
+pub const MAX_SURGE_STEP_BPS: u32 = 10_000;
+
 /// Settlement batch threshold: 100 HBAR (in tinybars)
 pub const SETTLEMENT_BATCH_THRESHOLD: Amount = 10_000_000_000;
 
@@ -148,6 +206,79 @@ pub const MAX_SOURCE_L1S_PER_L2: usize = 100;
 /// Maximum source L2s that can be merged into a single L2
 pub const MAX_SOURCE_L2S_PER_MERGE: usize = 20;
 
+// =============================================================================
+// Collection Constants
+// =============================================================================
+
+/// Maximum number of items in a collection
+pub const MAX_COLLECTION_ITEMS: usize = 100;
+
+/// Maximum weight of a single collection item
+pub const MAX_COLLECTION_ITEM_WEIGHT: u32 = 1000;
+
+// =============================================================================
+// Invoice Constants
+// =============================================================================
+
+/// Maximum invoice memo length in bytes
+pub const MAX_INVOICE_MEMO_LENGTH: usize = 500;
+
+/// Default time an invoice stays payable (7 days)
+pub const DEFAULT_INVOICE_EXPIRY_MS: u64 = 604_800_000;
+
+// =============================================================================
+// Capability Constants
+// =============================================================================
+
+/// Default lifetime of a capability token (24 hours)
+pub const DEFAULT_CAPABILITY_EXPIRY_MS: u64 = 86_400_000;
+
+// =============================================================================
+// Group Constants
+// =============================================================================
+
+/// Maximum members in a group membership list
+pub const MAX_GROUP_MEMBERS: usize = 10_000;
+
+/// Maximum group name length in bytes
+pub const MAX_GROUP_NAME_LENGTH: usize = 100;
+
+/// Maximum groups a piece of content can grant access to
+pub const MAX_GROUPS_PER_CONTENT: usize = 16;
+
+/// Default time a cached membership list is trusted before it is
+/// refreshed from the group owner (5 minutes)
+pub const DEFAULT_GROUP_CACHE_TTL_MS: u64 = 300_000;
+
+// =============================================================================
+// Attestation Constants
+// =============================================================================
+
+/// Default time a verified (or refuted) attestation claim is trusted
+/// before it is checked on-chain again (1 hour)
+pub const DEFAULT_ATTESTATION_CACHE_TTL_MS: u64 = 3_600_000;
+
+// =============================================================================
+// Snapshot Constants
+// =============================================================================
+
+/// Maximum announcements in a state snapshot (keeps snapshots well under
+/// `MAX_MESSAGE_SIZE`)
+pub const MAX_SNAPSHOT_ANNOUNCEMENTS: usize = 2_000;
+
+/// Maximum peer records in a state snapshot
+pub const MAX_SNAPSHOT_PEERS: usize = 500;
+
+// =============================================================================
+// Storefront Constants
+// =============================================================================
+
+/// Maximum items listed in a storefront (keeps it within one DHT record)
+pub const MAX_STOREFRONT_ITEMS: usize = 500;
+
+/// Maximum featured items in a storefront
+pub const MAX_STOREFRONT_FEATURED: usize = 12;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -225,4 +356,10 @@ mod tests {
         assert_eq!(MAX_SOURCE_L1S_PER_L2, 100);
         assert_eq!(MAX_SOURCE_L2S_PER_MERGE, 20);
     }
+
+    #[test]
+    fn test_collection_limits() {
+        assert_eq!(MAX_COLLECTION_ITEMS, 100);
+        assert_eq!(MAX_COLLECTION_ITEM_WEIGHT, 1000);
+    }
 }
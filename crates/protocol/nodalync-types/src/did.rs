@@ -0,0 +1,105 @@
+//! DID documents for Nodalync identities.
+//!
+//! Every identity has a `did:key` identifier derived from its Ed25519
+//! public key (see [`nodalync_crypto::did_from_public_key`]). The DID
+//! document is the W3C DID Core JSON form of that identifier, with a
+//! single `Ed25519VerificationKey2020` verification method used for both
+//! authentication and assertions. It is self-certifying: the key in the
+//! document must be the key encoded in its DID.
+
+use nodalync_crypto::{did_from_public_key, public_key_to_multibase, PublicKey};
+use serde::{Deserialize, Serialize};
+
+/// JSON-LD context of DID Core documents
+pub const DID_CONTEXT_V1: &str = "https://www.w3.org/ns/did/v1";
+
+/// JSON-LD context of the Ed25519 2020 verification suite
+pub const ED25519_2020_CONTEXT: &str = "https://w3id.org/security/suites/ed25519-2020/v1";
+
+/// Verification method type for Ed25519 keys
+pub const ED25519_VERIFICATION_KEY_2020: &str = "Ed25519VerificationKey2020";
+
+/// A verification method in a DID document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMethod {
+    /// DID URL of the method (`did:key:z6Mk...#z6Mk...`)
+    pub id: String,
+    /// Method type (`Ed25519VerificationKey2020`)
+    #[serde(rename = "type")]
+    pub method_type: String,
+    /// DID that controls the key
+    pub controller: String,
+    /// Multibase-encoded public key
+    pub public_key_multibase: String,
+}
+
+/// A DID document for a `did:key` identity.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocument {
+    /// JSON-LD contexts
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    /// The DID (`did:key:z6Mk...`)
+    pub id: String,
+    /// Keys of the DID subject
+    pub verification_method: Vec<VerificationMethod>,
+    /// Verification methods for authentication, by id
+    #[serde(default)]
+    pub authentication: Vec<String>,
+    /// Verification methods for signing assertions, by id
+    #[serde(default)]
+    pub assertion_method: Vec<String>,
+}
+
+impl DidDocument {
+    /// Create the DID document of a public key.
+    pub fn new(public_key: &PublicKey) -> Self {
+        let did = did_from_public_key(public_key);
+        let multibase = public_key_to_multibase(public_key);
+        let method_id = format!("{}#{}", did, multibase);
+
+        Self {
+            context: vec![DID_CONTEXT_V1.to_string(), ED25519_2020_CONTEXT.to_string()],
+            id: did.clone(),
+            verification_method: vec![VerificationMethod {
+                id: method_id.clone(),
+                method_type: ED25519_VERIFICATION_KEY_2020.to_string(),
+                controller: did,
+                public_key_multibase: multibase,
+            }],
+            authentication: vec![method_id.clone()],
+            assertion_method: vec![method_id],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::generate_identity;
+
+    #[test]
+    fn test_did_document_json() {
+        let (_, public_key) = generate_identity();
+        let doc = DidDocument::new(&public_key);
+        let did = did_from_public_key(&public_key);
+
+        let json: serde_json::Value = serde_json::to_value(&doc).unwrap();
+        assert_eq!(json["@context"][0], DID_CONTEXT_V1);
+        assert_eq!(json["id"], did.as_str());
+        let method = &json["verificationMethod"][0];
+        assert_eq!(method["type"], ED25519_VERIFICATION_KEY_2020);
+        assert_eq!(method["controller"], did.as_str());
+        assert_eq!(
+            method["publicKeyMultibase"],
+            public_key_to_multibase(&public_key).as_str()
+        );
+        assert_eq!(json["authentication"][0], method["id"]);
+        assert_eq!(json["assertionMethod"][0], method["id"]);
+
+        let parsed: DidDocument = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, doc);
+    }
+}
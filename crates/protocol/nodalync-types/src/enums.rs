@@ -12,6 +12,7 @@ use serde::{Deserialize, Serialize};
 /// - L1: Mentions (extracted atomic facts)
 /// - L2: Entity Graph (personal knowledge graph, always private)
 /// - L3: Insights (emergent synthesis)
+/// - Collection: Curated, ordered list of other content
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 #[repr(u8)]
 #[non_exhaustive]
@@ -25,6 +26,8 @@ pub enum ContentType {
     L2 = 0x02,
     /// Insights (emergent synthesis)
     L3 = 0x03,
+    /// Curated collection of other content (optionally sold as a bundle)
+    Collection = 0x04,
 }
 
 impl ContentType {
@@ -38,6 +41,7 @@ impl ContentType {
             0x01 => Some(ContentType::L1),
             0x02 => Some(ContentType::L2),
             0x03 => Some(ContentType::L3),
+            0x04 => Some(ContentType::Collection),
             _ => None,
         }
     }
@@ -196,6 +200,7 @@ mod tests {
         assert_eq!(ContentType::L1 as u8, 0x01);
         assert_eq!(ContentType::L2 as u8, 0x02);
         assert_eq!(ContentType::L3 as u8, 0x03);
+        assert_eq!(ContentType::Collection as u8, 0x04);
     }
 
     #[test]
@@ -204,7 +209,8 @@ mod tests {
         assert_eq!(ContentType::from_u8(0x01), Some(ContentType::L1));
         assert_eq!(ContentType::from_u8(0x02), Some(ContentType::L2));
         assert_eq!(ContentType::from_u8(0x03), Some(ContentType::L3));
-        assert_eq!(ContentType::from_u8(0x04), None);
+        assert_eq!(ContentType::from_u8(0x04), Some(ContentType::Collection));
+        assert_eq!(ContentType::from_u8(0x05), None);
         assert_eq!(ContentType::from_u8(0xFF), None);
     }
 
@@ -214,6 +220,7 @@ mod tests {
         assert_eq!(ContentType::L1.to_u8(), 0x01);
         assert_eq!(ContentType::L2.to_u8(), 0x02);
         assert_eq!(ContentType::L3.to_u8(), 0x03);
+        assert_eq!(ContentType::Collection.to_u8(), 0x04);
     }
 
     #[test]
@@ -223,6 +230,7 @@ mod tests {
             ContentType::L1,
             ContentType::L2,
             ContentType::L3,
+            ContentType::Collection,
         ] {
             assert_eq!(ContentType::from_u8(ct.to_u8()), Some(ct));
         }
@@ -2,10 +2,40 @@
 //!
 //! This module defines protocol-level error codes (Appendix C) and
 //! the main error type used across all Nodalync crates.
+//!
+//! [`ErrorCode`] is the shared registry every crate maps its errors into:
+//! each code has a stable numeric value, a [`Severity`], and a flag for
+//! whether the same request may succeed when retried.
 
+use nodalync_crypto::CryptoError;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+/// How serious an error is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// An expected condition the caller can recover from (content not
+    /// found, payment or a challenge answer required, a busy or
+    /// unreachable peer).
+    Warning,
+    /// The request failed and must change before it can succeed.
+    Error,
+    /// An integrity failure or a fault in the node itself (a bad hash,
+    /// signature or nonce, an internal error).
+    Critical,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+            Severity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
 /// Protocol error codes from Appendix C.
 ///
 /// These codes are used in protocol messages to communicate error conditions
@@ -29,6 +59,8 @@ pub enum ErrorCode {
     RateLimited = 0x0005,
     /// Requested version not found
     VersionNotFound = 0x0006,
+    /// Proof of possession required: sign the provider's challenge and retry
+    ChallengeRequired = 0x0007,
 
     // =========================================================================
     // Channel Errors (0x0100 - 0x01FF)
@@ -94,6 +126,44 @@ pub enum ErrorCode {
 }
 
 impl ErrorCode {
+    /// Every registered error code, in numeric order.
+    pub const ALL: &'static [ErrorCode] = &[
+        Self::NotFound,
+        Self::AccessDenied,
+        Self::PaymentRequired,
+        Self::PaymentInvalid,
+        Self::RateLimited,
+        Self::VersionNotFound,
+        Self::ChallengeRequired,
+        Self::ChannelNotFound,
+        Self::ChannelClosed,
+        Self::InsufficientBalance,
+        Self::InvalidNonce,
+        Self::InvalidSignature,
+        Self::InvalidHash,
+        Self::InvalidProvenance,
+        Self::InvalidVersion,
+        Self::InvalidManifest,
+        Self::ContentTooLarge,
+        Self::L2InvalidStructure,
+        Self::L2MissingSource,
+        Self::L2EntityLimit,
+        Self::L2RelationshipLimit,
+        Self::L2InvalidEntityRef,
+        Self::L2CycleDetected,
+        Self::L2InvalidUri,
+        Self::L2CannotPublish,
+        Self::PeerNotFound,
+        Self::ConnectionFailed,
+        Self::Timeout,
+        Self::InternalError,
+    ];
+
+    /// Look up the error code with numeric value `code`.
+    pub fn from_code(code: u16) -> Option<Self> {
+        Self::ALL.iter().copied().find(|c| c.code() == code)
+    }
+
     /// Returns true if this is a query-related error (0x0001-0x00FF)
     pub fn is_query_error(&self) -> bool {
         let code = *self as u16;
@@ -123,6 +193,40 @@ impl ErrorCode {
         *self as u16
     }
 
+    /// How serious this error is.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::NotFound
+            | Self::VersionNotFound
+            | Self::PaymentRequired
+            | Self::ChallengeRequired
+            | Self::RateLimited
+            | Self::ChannelNotFound
+            | Self::PeerNotFound
+            | Self::ConnectionFailed
+            | Self::Timeout => Severity::Warning,
+            Self::InvalidNonce
+            | Self::InvalidSignature
+            | Self::InvalidHash
+            | Self::InvalidProvenance
+            | Self::InternalError => Severity::Critical,
+            _ => Severity::Error,
+        }
+    }
+
+    /// Whether the same request may succeed when retried, after waiting
+    /// or answering a challenge but without otherwise changing it.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::RateLimited
+                | Self::ChallengeRequired
+                | Self::PeerNotFound
+                | Self::ConnectionFailed
+                | Self::Timeout
+        )
+    }
+
     /// Get a user-friendly suggestion for recovering from this error.
     ///
     /// Returns actionable hints that help users understand what went wrong
@@ -136,6 +240,7 @@ impl ErrorCode {
             Self::PaymentInvalid => Some("Check payment amount, signature, or channel state."),
             Self::RateLimited => Some("Wait before retrying. Consider reducing query frequency."),
             Self::VersionNotFound => Some("The requested version doesn't exist. Use 'nodalync versions' to list available versions."),
+            Self::ChallengeRequired => Some("The provider requires a signed challenge. Retry the query to answer it."),
 
             // Channel errors
             Self::ChannelNotFound => Some("Open a channel first with 'nodalync channel open'."),
@@ -198,6 +303,7 @@ impl std::fmt::Display for ErrorCode {
             ErrorCode::PaymentInvalid => write!(f, "PAYMENT_INVALID"),
             ErrorCode::RateLimited => write!(f, "RATE_LIMITED"),
             ErrorCode::VersionNotFound => write!(f, "VERSION_NOT_FOUND"),
+            ErrorCode::ChallengeRequired => write!(f, "CHALLENGE_REQUIRED"),
             ErrorCode::ChannelNotFound => write!(f, "CHANNEL_NOT_FOUND"),
             ErrorCode::ChannelClosed => write!(f, "CHANNEL_CLOSED"),
             ErrorCode::InsufficientBalance => write!(f, "INSUFFICIENT_BALANCE"),
@@ -224,6 +330,36 @@ impl std::fmt::Display for ErrorCode {
     }
 }
 
+impl From<ErrorCode> for u16 {
+    fn from(code: ErrorCode) -> Self {
+        code.code()
+    }
+}
+
+impl TryFrom<u16> for ErrorCode {
+    type Error = u16;
+
+    /// Look up a numeric code, returning it back if it is not registered.
+    fn try_from(code: u16) -> std::result::Result<Self, Self::Error> {
+        Self::from_code(code).ok_or(code)
+    }
+}
+
+impl From<&CryptoError> for ErrorCode {
+    fn from(error: &CryptoError) -> Self {
+        match error {
+            CryptoError::SignatureVerificationFailed
+            | CryptoError::InvalidKeyLength { .. }
+            | CryptoError::InvalidPublicKey => ErrorCode::InvalidSignature,
+            CryptoError::DecryptionFailed => ErrorCode::InvalidHash,
+            CryptoError::InvalidPeerIdFormat(_)
+            | CryptoError::InvalidPeerIdPrefix(_)
+            | CryptoError::InvalidBase58(_)
+            | CryptoError::InvalidDid(_) => ErrorCode::PeerNotFound,
+        }
+    }
+}
+
 /// Main error type for all Nodalync operations.
 ///
 /// This error type is used across all Nodalync crates to provide
@@ -318,6 +454,10 @@ impl NodalyncError {
             NodalyncError::PaymentValidation(_) => Some(ErrorCode::PaymentInvalid),
             NodalyncError::Channel(_) => Some(ErrorCode::ChannelNotFound),
             NodalyncError::Network(_) => Some(ErrorCode::ConnectionFailed),
+            NodalyncError::Storage(_) | NodalyncError::Settlement(_) => {
+                Some(ErrorCode::InternalError)
+            }
+            NodalyncError::InvalidInput(_) => Some(ErrorCode::InvalidManifest),
             _ => None,
         }
     }
@@ -339,6 +479,7 @@ mod tests {
         assert_eq!(ErrorCode::PaymentInvalid as u16, 0x0004);
         assert_eq!(ErrorCode::RateLimited as u16, 0x0005);
         assert_eq!(ErrorCode::VersionNotFound as u16, 0x0006);
+        assert_eq!(ErrorCode::ChallengeRequired as u16, 0x0007);
 
         // Channel errors
         assert_eq!(ErrorCode::ChannelNotFound as u16, 0x0100);
@@ -439,6 +580,45 @@ mod tests {
         assert_eq!(deserialized, code);
     }
 
+    #[test]
+    fn test_error_code_registry() {
+        for (i, code) in ErrorCode::ALL.iter().enumerate() {
+            assert_eq!(ErrorCode::from_code(code.code()), Some(*code));
+            assert_eq!(ErrorCode::try_from(u16::from(*code)), Ok(*code));
+            if i > 0 {
+                assert!(ErrorCode::ALL[i - 1].code() < code.code());
+            }
+        }
+        assert_eq!(ErrorCode::from_code(0x0008), None);
+        assert_eq!(ErrorCode::try_from(0x0400), Err(0x0400));
+    }
+
+    #[test]
+    fn test_error_code_severity_and_retry() {
+        assert_eq!(ErrorCode::NotFound.severity(), Severity::Warning);
+        assert_eq!(ErrorCode::InvalidManifest.severity(), Severity::Error);
+        assert_eq!(ErrorCode::InvalidSignature.severity(), Severity::Critical);
+        assert_eq!(ErrorCode::InternalError.severity(), Severity::Critical);
+        assert_eq!(Severity::Critical.to_string(), "critical");
+
+        assert!(ErrorCode::RateLimited.is_retryable());
+        assert!(ErrorCode::Timeout.is_retryable());
+        assert!(!ErrorCode::NotFound.is_retryable());
+        assert!(!ErrorCode::InternalError.is_retryable());
+    }
+
+    #[test]
+    fn test_crypto_error_code() {
+        assert_eq!(
+            ErrorCode::from(&CryptoError::SignatureVerificationFailed),
+            ErrorCode::InvalidSignature
+        );
+        assert_eq!(
+            ErrorCode::from(&CryptoError::InvalidDid("did:x".into())),
+            ErrorCode::PeerNotFound
+        );
+    }
+
     #[test]
     fn test_error_code_copy() {
         let code = ErrorCode::NotFound;
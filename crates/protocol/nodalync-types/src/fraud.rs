@@ -0,0 +1,152 @@
+//! Fraud proofs against providers serving the wrong content.
+//!
+//! A provider that delivers plaintext content signs a
+//! [`DeliveryCommitment`] naming the hash of the bytes it sent. If those
+//! bytes don't hash to the content the requester asked for, the provider
+//! has signed its own evidence: the requester wraps the commitment in a
+//! [`FraudProof`], signs it, and broadcasts it. Anyone can check the proof
+//! without the content, since the served hash and the requested hash are
+//! both covered by the provider's signature.
+
+use nodalync_crypto::{content_hash, Hash, PeerId, PublicKey, Signature, Timestamp};
+use serde::{Deserialize, Serialize};
+
+/// A provider's signed statement of what it delivered for a query.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DeliveryCommitment {
+    /// Hash of the content bytes as sent
+    pub served_hash: Hash,
+    /// Provider's public key, for signature verification
+    pub provider_key: PublicKey,
+    /// Provider's signature over the delivery message
+    pub signature: Signature,
+}
+
+/// A requester's signed proof that a provider served the wrong content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct FraudProof {
+    /// Hash of the proof terms (see [`FraudProof::compute_hash`])
+    pub hash: Hash,
+    /// Content that was requested
+    pub content_hash: Hash,
+    /// Payment the delivery was for, from the provider's receipt
+    pub payment_id: Hash,
+    /// Provider that served the content
+    pub provider: PeerId,
+    /// What the provider committed to delivering
+    pub delivery: DeliveryCommitment,
+    /// Requester the content was served to
+    pub requester: PeerId,
+    /// Requester's public key, for signature verification
+    pub requester_key: PublicKey,
+    /// When the proof was created
+    pub created_at: Timestamp,
+    /// Requester's signature over `hash`
+    pub signature: Signature,
+}
+
+impl FraudProof {
+    /// Create an unsigned fraud proof.
+    ///
+    /// The hash is computed from the terms; the signature is left zeroed
+    /// until the requester signs it.
+    pub fn new(
+        content_hash: Hash,
+        payment_id: Hash,
+        provider: PeerId,
+        delivery: DeliveryCommitment,
+        requester: PeerId,
+        requester_key: PublicKey,
+        created_at: Timestamp,
+    ) -> Self {
+        let mut proof = Self {
+            hash: Hash([0u8; 32]),
+            content_hash,
+            payment_id,
+            provider,
+            delivery,
+            requester,
+            requester_key,
+            created_at,
+            signature: Signature::from_bytes([0u8; 64]),
+        };
+        proof.hash = proof.compute_hash();
+        proof
+    }
+
+    /// Compute the hash of the proof terms.
+    ///
+    /// Covers every field except `hash` and `signature`, including the
+    /// provider's commitment, prefixed with a domain tag.
+    pub fn compute_hash(&self) -> Hash {
+        let mut data = Vec::with_capacity(320);
+        data.extend_from_slice(b"nodalync:fraud");
+        data.extend_from_slice(&self.content_hash.0);
+        data.extend_from_slice(&self.payment_id.0);
+        data.extend_from_slice(&self.provider.0);
+        data.extend_from_slice(&self.delivery.served_hash.0);
+        data.extend_from_slice(&self.delivery.provider_key.0);
+        data.extend_from_slice(&self.delivery.signature.0);
+        data.extend_from_slice(&self.requester.0);
+        data.extend_from_slice(&self.requester_key.0);
+        data.extend_from_slice(&self.created_at.to_be_bytes());
+        content_hash(&data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+
+    fn test_proof() -> FraudProof {
+        let (_, provider_key) = generate_identity();
+        let (_, requester_key) = generate_identity();
+        FraudProof::new(
+            content_hash(b"requested"),
+            content_hash(b"payment"),
+            peer_id_from_public_key(&provider_key),
+            DeliveryCommitment {
+                served_hash: content_hash(b"served"),
+                provider_key,
+                signature: Signature::from_bytes([2u8; 64]),
+            },
+            peer_id_from_public_key(&requester_key),
+            requester_key,
+            1_000,
+        )
+    }
+
+    #[test]
+    fn test_fraud_proof_hash_covers_terms() {
+        let proof = test_proof();
+        assert_eq!(proof.hash, proof.compute_hash());
+
+        let mut changed = proof.clone();
+        changed.delivery.served_hash = content_hash(b"other");
+        assert_ne!(changed.compute_hash(), proof.hash);
+
+        let mut changed = proof.clone();
+        changed.delivery.signature = Signature::from_bytes([3u8; 64]);
+        assert_ne!(changed.compute_hash(), proof.hash);
+
+        let mut changed = proof.clone();
+        changed.created_at += 1;
+        assert_ne!(changed.compute_hash(), proof.hash);
+
+        // The requester's signature is not part of the terms
+        let mut signed = proof.clone();
+        signed.signature = Signature::from_bytes([1u8; 64]);
+        assert_eq!(signed.compute_hash(), proof.hash);
+    }
+
+    #[test]
+    fn test_fraud_proof_serialization() {
+        let proof = test_proof();
+        let json = serde_json::to_string(&proof).unwrap();
+        let parsed: FraudProof = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, proof);
+    }
+}
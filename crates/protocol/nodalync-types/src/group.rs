@@ -0,0 +1,167 @@
+//! Access groups backed by signed membership lists.
+//!
+//! Allowlists of individual peers do not scale to organizations. A group
+//! owner instead signs a membership list, and content can name the group
+//! in `AccessControl::groups`. The group is identified by a stable ID
+//! derived from its owner and name, so the list can be updated (with a
+//! higher version) without touching every manifest that references it.
+//! Like invoices, the list carries the owner's public key so it can be
+//! verified without a key lookup.
+
+use nodalync_crypto::{content_hash, Hash, PeerId, PublicKey, Signature, Timestamp};
+use serde::{Deserialize, Serialize};
+
+/// A signed list of the members of an access group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Group {
+    /// Stable group identifier (see [`Group::compute_id`])
+    pub id: Hash,
+    /// Peer that maintains the group
+    pub owner: PeerId,
+    /// Owner's public key, for signature verification
+    pub owner_key: PublicKey,
+    /// Human-readable group name
+    pub name: String,
+    /// Members of the group
+    pub members: Vec<PeerId>,
+    /// Version of the list; a newer list replaces an older one
+    pub version: u64,
+    /// When this version was signed
+    pub updated_at: Timestamp,
+    /// Hash of this version's terms (see [`Group::compute_hash`])
+    pub hash: Hash,
+    /// Owner's signature over `hash`
+    pub signature: Signature,
+}
+
+impl Group {
+    /// Create an unsigned group list.
+    ///
+    /// The ID and hash are computed from the terms; the signature is left
+    /// zeroed until the owner signs it.
+    pub fn new(
+        owner: PeerId,
+        owner_key: PublicKey,
+        name: impl Into<String>,
+        members: Vec<PeerId>,
+        version: u64,
+        updated_at: Timestamp,
+    ) -> Self {
+        let name = name.into();
+        let mut group = Self {
+            id: Self::compute_id(&owner, &name),
+            owner,
+            owner_key,
+            name,
+            members,
+            version,
+            updated_at,
+            hash: Hash([0u8; 32]),
+            signature: Signature::from_bytes([0u8; 64]),
+        };
+        group.hash = group.compute_hash();
+        group
+    }
+
+    /// Compute the stable ID of a group: `H(owner || name)`.
+    pub fn compute_id(owner: &PeerId, name: &str) -> Hash {
+        let mut data = Vec::with_capacity(20 + name.len());
+        data.extend_from_slice(&owner.0);
+        data.extend_from_slice(name.as_bytes());
+        content_hash(&data)
+    }
+
+    /// Compute the hash of this version's terms.
+    ///
+    /// Covers every field except `hash` and `signature`.
+    pub fn compute_hash(&self) -> Hash {
+        let mut data = Vec::with_capacity(128 + self.name.len() + 20 * self.members.len());
+        data.extend_from_slice(&self.id.0);
+        data.extend_from_slice(&self.owner.0);
+        data.extend_from_slice(&self.owner_key.0);
+        data.extend_from_slice(&(self.name.len() as u32).to_be_bytes());
+        data.extend_from_slice(self.name.as_bytes());
+        data.extend_from_slice(&(self.members.len() as u32).to_be_bytes());
+        for member in &self.members {
+            data.extend_from_slice(&member.0);
+        }
+        data.extend_from_slice(&self.version.to_be_bytes());
+        data.extend_from_slice(&self.updated_at.to_be_bytes());
+        content_hash(&data)
+    }
+
+    /// Check if a peer is a member of the group.
+    ///
+    /// The owner is always considered a member.
+    pub fn is_member(&self, peer: &PeerId) -> bool {
+        *peer == self.owner || self.members.contains(peer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+
+    fn test_group(members: Vec<PeerId>) -> Group {
+        let (_, public_key) = generate_identity();
+        Group::new(
+            peer_id_from_public_key(&public_key),
+            public_key,
+            "research",
+            members,
+            1,
+            1_000,
+        )
+    }
+
+    #[test]
+    fn test_group_id_is_stable() {
+        let group = test_group(vec![PeerId([1u8; 20])]);
+        assert_eq!(group.id, Group::compute_id(&group.owner, "research"));
+
+        let updated = Group::new(
+            group.owner,
+            group.owner_key,
+            "research",
+            vec![PeerId([1u8; 20]), PeerId([2u8; 20])],
+            2,
+            2_000,
+        );
+        assert_eq!(updated.id, group.id);
+        assert_ne!(updated.hash, group.hash);
+        assert_ne!(Group::compute_id(&group.owner, "finance"), group.id);
+    }
+
+    #[test]
+    fn test_group_hash_covers_terms() {
+        let group = test_group(vec![PeerId([1u8; 20])]);
+        assert_eq!(group.hash, group.compute_hash());
+
+        let mut changed = group.clone();
+        changed.members.push(PeerId([2u8; 20]));
+        assert_ne!(changed.compute_hash(), group.hash);
+
+        let mut changed = group.clone();
+        changed.version += 1;
+        assert_ne!(changed.compute_hash(), group.hash);
+    }
+
+    #[test]
+    fn test_group_membership() {
+        let member = PeerId([1u8; 20]);
+        let group = test_group(vec![member]);
+        assert!(group.is_member(&member));
+        assert!(group.is_member(&group.owner));
+        assert!(!group.is_member(&PeerId([2u8; 20])));
+    }
+
+    #[test]
+    fn test_group_serialization() {
+        let group = test_group(vec![PeerId([1u8; 20])]);
+        let json = serde_json::to_string(&group).unwrap();
+        let decoded: Group = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, group);
+    }
+}
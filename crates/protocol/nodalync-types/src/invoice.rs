@@ -0,0 +1,144 @@
+//! Invoices for payments outside the query path.
+//!
+//! An invoice asks a payer to send `amount` to the payee, for example to
+//! pay for a dataset shared out of band. It is identified by a hash over
+//! its terms and signed by the payee's identity key, which travels with
+//! the invoice so the payer can verify it without a key lookup.
+
+use nodalync_crypto::{content_hash, Hash, PeerId, PublicKey, Signature, Timestamp};
+use serde::{Deserialize, Serialize};
+
+use crate::Amount;
+
+/// A signed request for payment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Invoice {
+    /// Hash of the invoice terms (see [`Invoice::compute_hash`])
+    pub hash: Hash,
+    /// Peer to be paid
+    pub payee: PeerId,
+    /// Payee's public key, for signature verification
+    pub payee_key: PublicKey,
+    /// Amount requested
+    pub amount: Amount,
+    /// Free-form description shown to the payer
+    pub memo: String,
+    /// Content the invoice is for, if any
+    pub reference: Option<Hash>,
+    /// When the invoice was created
+    pub created_at: Timestamp,
+    /// When the invoice stops being payable
+    pub expires_at: Timestamp,
+    /// Payee's signature over `hash`
+    pub signature: Signature,
+}
+
+impl Invoice {
+    /// Create an unsigned invoice.
+    ///
+    /// The hash is computed from the terms; the signature is left zeroed
+    /// until the payee signs it.
+    pub fn new(
+        payee: PeerId,
+        payee_key: PublicKey,
+        amount: Amount,
+        memo: impl Into<String>,
+        reference: Option<Hash>,
+        created_at: Timestamp,
+        expires_at: Timestamp,
+    ) -> Self {
+        let mut invoice = Self {
+            hash: Hash([0u8; 32]),
+            payee,
+            payee_key,
+            amount,
+            memo: memo.into(),
+            reference,
+            created_at,
+            expires_at,
+            signature: Signature::from_bytes([0u8; 64]),
+        };
+        invoice.hash = invoice.compute_hash();
+        invoice
+    }
+
+    /// Compute the hash of the invoice terms.
+    ///
+    /// Covers every field except `hash` and `signature`.
+    pub fn compute_hash(&self) -> Hash {
+        let mut data = Vec::with_capacity(128 + self.memo.len());
+        data.extend_from_slice(&self.payee.0);
+        data.extend_from_slice(&self.payee_key.0);
+        data.extend_from_slice(&self.amount.to_be_bytes());
+        data.extend_from_slice(&(self.memo.len() as u64).to_be_bytes());
+        data.extend_from_slice(self.memo.as_bytes());
+        match &self.reference {
+            Some(reference) => {
+                data.push(1);
+                data.extend_from_slice(&reference.0);
+            }
+            None => data.push(0),
+        }
+        data.extend_from_slice(&self.created_at.to_be_bytes());
+        data.extend_from_slice(&self.expires_at.to_be_bytes());
+        content_hash(&data)
+    }
+
+    /// Check if the invoice has expired at the given time.
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        now >= self.expires_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+
+    fn test_invoice() -> Invoice {
+        let (_, public_key) = generate_identity();
+        Invoice::new(
+            peer_id_from_public_key(&public_key),
+            public_key,
+            500,
+            "Climate dataset",
+            Some(content_hash(b"dataset")),
+            1_000,
+            2_000,
+        )
+    }
+
+    #[test]
+    fn test_invoice_hash_covers_terms() {
+        let invoice = test_invoice();
+        assert_eq!(invoice.hash, invoice.compute_hash());
+
+        let mut changed = invoice.clone();
+        changed.amount = 501;
+        assert_ne!(changed.compute_hash(), invoice.hash);
+
+        let mut changed = invoice.clone();
+        changed.reference = None;
+        assert_ne!(changed.compute_hash(), invoice.hash);
+
+        let mut changed = invoice.clone();
+        changed.memo.push('!');
+        assert_ne!(changed.compute_hash(), invoice.hash);
+    }
+
+    #[test]
+    fn test_invoice_expiry() {
+        let invoice = test_invoice();
+        assert!(!invoice.is_expired(1_999));
+        assert!(invoice.is_expired(2_000));
+    }
+
+    #[test]
+    fn test_invoice_serialization() {
+        let invoice = test_invoice();
+        let json = serde_json::to_string(&invoice).unwrap();
+        let decoded: Invoice = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, invoice);
+    }
+}
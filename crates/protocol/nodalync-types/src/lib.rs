@@ -10,9 +10,22 @@
 //! - [`constants`] - Protocol constants (limits, timing, economics)
 //! - [`error`] - Error codes and the main error type
 //! - [`manifest`] - Content manifest and metadata types
+//! - [`canonical`] - Signed pointers choosing the canonical version among forks
 //! - [`provenance`] - Provenance chain types
+//! - [`attribution`] - Signed attribution certificates for derived content
 //! - [`content`] - L1 mentions and summaries
+//! - [`collection`] - Curated collections of content
+//! - [`tags`] - Hierarchical tag normalization
 //! - [`channel`] - Payment channel types
+//! - [`invoice`] - Signed payment requests
+//! - [`capability`] - Signed tokens granting access to unpublished content
+//! - [`group`] - Signed membership lists for group-based access control
+//! - [`tombstone`] - Signed withdrawals of content from the network
+//! - [`report`] - Signed content reports for moderation
+//! - [`revocation`] - Self-signed certificates revoking a compromised identity key
+//! - [`replica`] - Signed delegations letting another node serve a publisher's catalog
+//! - [`fraud`] - Signed delivery commitments and fraud proofs against providers
+//! - [`did`] - DID documents for `did:key` identities
 //! - [`settlement`] - On-chain settlement types
 //!
 //! # Example
@@ -51,15 +64,28 @@
 /// Protocol version (from Cargo.toml).
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+pub mod attribution;
+pub mod canonical;
+pub mod capability;
 pub mod channel;
+pub mod collection;
 pub mod constants;
 pub mod content;
+pub mod did;
 pub mod enums;
 pub mod error;
+pub mod fraud;
+pub mod group;
+pub mod invoice;
 pub mod l2;
 pub mod manifest;
 pub mod provenance;
+pub mod replica;
+pub mod report;
+pub mod revocation;
 pub mod settlement;
+pub mod tags;
+pub mod tombstone;
 
 // Re-export all public types at the crate root for convenience
 
@@ -73,20 +99,65 @@ pub use enums::{
 pub use constants::*;
 
 // Error types
-pub use error::{ErrorCode, NodalyncError, Result};
+pub use error::{ErrorCode, NodalyncError, Result, Severity};
 
 // Manifest types
-pub use manifest::{AccessControl, Economics, Manifest, Metadata, Version};
+pub use manifest::{
+    AccessControl, ChunkTree, ContentSection, Economics, Manifest, Metadata, PreviewPolicy,
+    PricingRule, PublisherBond, QuotaLimit, QuotaPolicy, ReferralPolicy, TrialPolicy, Version,
+};
+
+// Canonical version pointers
+pub use canonical::CanonicalVersion;
 
 // Provenance types
 pub use provenance::{Provenance, ProvenanceEntry};
 
+// Attribution types
+pub use attribution::{
+    AttestationReference, AttributionCertificate, AttributionContext, AttributionProof,
+    SourceAttribution,
+};
+
 // Content types
 pub use content::{L1Summary, Mention, SourceLocation};
 
+// Collection types
+pub use collection::{Collection, CollectionItem};
+
+// Tag taxonomy
+pub use tags::{normalize_tag, normalize_tags, tag_parent, tag_path, TAG_SEPARATOR};
+
 // Channel types
 pub use channel::{Channel, Payment, PendingClose, PendingDispute};
 
+// Invoice types
+pub use invoice::Invoice;
+
+// Capability types
+pub use capability::CapabilityToken;
+
+// Group types
+pub use group::Group;
+
+// Tombstone types
+pub use tombstone::Tombstone;
+
+// Report types
+pub use report::{ContentReport, ReportReason, MAX_REPORT_COMMENT_LENGTH};
+
+// Revocation types
+pub use revocation::RevocationCertificate;
+
+// Replica types
+pub use replica::ReplicaDelegation;
+
+// Fraud proof types
+pub use fraud::{DeliveryCommitment, FraudProof};
+
+// DID types
+pub use did::{DidDocument, VerificationMethod};
+
 // Settlement types
 pub use settlement::{Distribution, SettlementBatch, SettlementEntry};
 
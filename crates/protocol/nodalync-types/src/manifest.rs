@@ -3,9 +3,12 @@
 //! This module defines the `Manifest` struct and its component types
 //! as specified in Protocol Specification §4.3, §4.6, §4.7, §4.8.
 
-use nodalync_crypto::{Hash, PeerId, Timestamp};
+use std::ops::Range;
+
+use nodalync_crypto::{chunk_hash, section_hash, Hash, PeerId, Timestamp};
 use serde::{Deserialize, Serialize};
 
+use crate::constants::{BASIS_POINTS_DENOMINATOR, CHUNK_SIZE, QUOTA_PERIOD_MS};
 use crate::enums::{ContentType, Currency, Visibility};
 use crate::provenance::Provenance;
 use crate::Amount;
@@ -28,6 +31,9 @@ pub struct Version {
     pub root: Hash,
     /// Creation timestamp
     pub timestamp: Timestamp,
+    /// Set locally when another version of the lineage has the same number
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub forked: bool,
 }
 
 impl Version {
@@ -40,6 +46,7 @@ impl Version {
             previous: None,
             root: content_hash,
             timestamp,
+            forked: false,
         }
     }
 
@@ -56,6 +63,7 @@ impl Version {
             previous: Some(previous_hash),
             root: previous_version.root,
             timestamp,
+            forked: false,
         }
     }
 
@@ -94,6 +102,27 @@ pub struct Metadata {
     pub content_size: u64,
     /// MIME type if applicable
     pub mime_type: Option<String>,
+    /// URI of the schema that `fields` conforms to
+    /// (e.g. `nodalync:schema/dataset/v1`, max 256 chars)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    /// Structured fields as a JSON object, validated against `schema`
+    /// (max 16 KiB)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fields: Option<String>,
+    /// Redaction rules for the summary shown before payment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_policy: Option<PreviewPolicy>,
+    /// Per-chunk hashes, for content larger than one chunk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunks: Option<ChunkTree>,
+    /// Sections that can be bought on their own (max 100), in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<ContentSection>,
+    /// Per-unit hashes for metered delivery, where each unit is paid for
+    /// as it is delivered and a reader may stop early
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metering: Option<ChunkTree>,
 }
 
 impl Metadata {
@@ -105,6 +134,12 @@ impl Metadata {
             tags: Vec::new(),
             content_size,
             mime_type: None,
+            schema: None,
+            fields: None,
+            preview_policy: None,
+            chunks: None,
+            sections: Vec::new(),
+            metering: None,
         }
     }
 
@@ -125,6 +160,304 @@ impl Metadata {
         self.mime_type = Some(mime_type.into());
         self
     }
+
+    /// Set structured fields (a JSON object) and the schema they conform to.
+    pub fn with_fields(mut self, schema: impl Into<String>, fields: impl Into<String>) -> Self {
+        self.schema = Some(schema.into());
+        self.fields = Some(fields.into());
+        self
+    }
+
+    /// Set the preview policy.
+    pub fn with_preview_policy(mut self, policy: PreviewPolicy) -> Self {
+        self.preview_policy = Some(policy);
+        self
+    }
+
+    /// Set the priced sections.
+    pub fn with_sections(mut self, sections: Vec<ContentSection>) -> Self {
+        self.sections = sections;
+        self
+    }
+
+    /// Look up a section by index.
+    pub fn section(&self, index: u32) -> Option<&ContentSection> {
+        self.sections.get(index as usize)
+    }
+
+    /// Number of metered units, or zero when the content isn't metered.
+    pub fn metered_units(&self) -> u32 {
+        self.metering.as_ref().map_or(0, |tree| tree.len() as u32)
+    }
+}
+
+/// Hashes of the fixed-size chunks of a piece of content.
+///
+/// The leaves of a one-level hash tree under the content hash: each chunk
+/// verifies on its own against its leaf, so a download split across several
+/// providers can check chunks as they arrive and fetch a bad one again from
+/// someone else, rather than only finding out from the content hash once
+/// everything has arrived.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ChunkTree {
+    /// Chunk size in bytes; the last chunk may be shorter
+    pub chunk_size: u64,
+    /// [`chunk_hash`] of each chunk, in order
+    pub hashes: Vec<Hash>,
+}
+
+impl ChunkTree {
+    /// Hash content in chunks of `chunk_size` bytes.
+    pub fn build(content: &[u8], chunk_size: u64) -> Self {
+        let hashes = content
+            .chunks(chunk_size.max(1) as usize)
+            .enumerate()
+            .map(|(index, chunk)| chunk_hash(index as u32, chunk))
+            .collect();
+        Self { chunk_size, hashes }
+    }
+
+    /// The chunk tree a publisher records for content: `None` when the
+    /// content fits in a single [`CHUNK_SIZE`] chunk.
+    pub fn for_content(content: &[u8]) -> Option<Self> {
+        (content.len() as u64 > CHUNK_SIZE).then(|| Self::build(content, CHUNK_SIZE))
+    }
+
+    /// Number of chunks content of `content_size` bytes splits into.
+    pub fn chunk_count(content_size: u64, chunk_size: u64) -> u64 {
+        if chunk_size == 0 {
+            return 0;
+        }
+        content_size.div_ceil(chunk_size)
+    }
+
+    /// Number of chunks.
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Whether there are no chunks.
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// Byte range of a chunk within content of `content_size` bytes.
+    ///
+    /// Returns `None` for an index past the last chunk.
+    pub fn chunk_range(&self, index: u32, content_size: u64) -> Option<Range<usize>> {
+        let start = u64::from(index).checked_mul(self.chunk_size)?;
+        if index as usize >= self.hashes.len() || start >= content_size {
+            return None;
+        }
+        let end = (start + self.chunk_size).min(content_size);
+        Some(start as usize..end as usize)
+    }
+
+    /// Check a chunk against its recorded hash.
+    pub fn verify_chunk(&self, index: u32, chunk: &[u8]) -> bool {
+        self.hashes
+            .get(index as usize)
+            .is_some_and(|expected| chunk_hash(index, chunk) == *expected)
+    }
+}
+
+/// A section of content sold on its own, at its own price.
+///
+/// A query can ask for just one section and pay its price rather than the
+/// price of the whole document. The section's hash binds the slice to its
+/// place in the content, so the buyer can check what they were served
+/// without having the rest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ContentSection {
+    /// Section title (max 200 chars)
+    pub title: String,
+    /// Byte offset of the section within the content
+    pub offset: u64,
+    /// Section length in bytes
+    pub length: u64,
+    /// Price to query the section alone
+    pub price: Amount,
+    /// [`section_hash`] of the section's bytes
+    pub hash: Hash,
+}
+
+impl ContentSection {
+    /// Describe the bytes at `range` of `content` as a section.
+    ///
+    /// # Panics
+    /// If `range` is out of bounds for `content`.
+    pub fn new(
+        title: impl Into<String>,
+        content: &[u8],
+        range: Range<usize>,
+        price: Amount,
+    ) -> Self {
+        let offset = range.start as u64;
+        let slice = &content[range];
+        Self {
+            title: title.into(),
+            offset,
+            length: slice.len() as u64,
+            price,
+            hash: section_hash(offset, slice),
+        }
+    }
+
+    /// Byte range of the section within the content.
+    pub fn range(&self) -> Range<usize> {
+        self.offset as usize..self.offset.saturating_add(self.length) as usize
+    }
+
+    /// Byte offset just past the end of the section.
+    pub fn end(&self) -> u64 {
+        self.offset.saturating_add(self.length)
+    }
+
+    /// Check a served slice against the recorded hash.
+    pub fn verify(&self, slice: &[u8]) -> bool {
+        slice.len() as u64 == self.length && section_hash(self.offset, slice) == self.hash
+    }
+}
+
+/// Redaction rules for a content preview.
+///
+/// The L1 summary that peers see before paying (in previews, search results
+/// and announcements) is generated from the content with these rules
+/// applied. Queried content is always delivered in full.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PreviewPolicy {
+    /// Regular expressions; matching text is replaced with
+    /// [`REDACTION_MARKER`](crate::REDACTION_MARKER)
+    #[serde(default)]
+    pub redact_patterns: Vec<String>,
+    /// Markdown section headings (case-insensitive) whose sections, including
+    /// subsections, are left out
+    #[serde(default)]
+    pub redact_sections: Vec<String>,
+}
+
+impl PreviewPolicy {
+    /// Create an empty policy.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a redaction pattern.
+    pub fn with_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.redact_patterns.push(pattern.into());
+        self
+    }
+
+    /// Add a redacted section heading.
+    pub fn with_section(mut self, heading: impl Into<String>) -> Self {
+        self.redact_sections.push(heading.into());
+        self
+    }
+
+    /// Whether the policy redacts nothing.
+    pub fn is_empty(&self) -> bool {
+        self.redact_patterns.is_empty() && self.redact_sections.is_empty()
+    }
+}
+
+/// Free reads an owner offers each requester before they must pay.
+///
+/// Lets casual browsers try paid content without being allowlisted. Each
+/// requester gets `reads` free reads of the whole content, or of only its
+/// first `bytes` bytes when set; after that they pay as usual.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct TrialPolicy {
+    /// Free reads per requester (1 to MAX_TRIAL_READS)
+    pub reads: u32,
+    /// Only this many leading bytes are free (None = the whole content)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+}
+
+impl TrialPolicy {
+    /// Offer `reads` free reads of the whole content.
+    pub fn new(reads: u32) -> Self {
+        Self { reads, bytes: None }
+    }
+
+    /// Only make the first `bytes` bytes free.
+    pub fn with_bytes(mut self, bytes: u64) -> Self {
+        self.bytes = Some(bytes);
+        self
+    }
+
+    /// Free reads left for a requester who has used `used`.
+    pub fn reads_left(&self, used: u32) -> u32 {
+        self.reads.saturating_sub(used)
+    }
+}
+
+/// Daily limits on how much each requester may read.
+///
+/// A requester who has made `queries` queries, or been served `bytes`
+/// bytes, since the start of the UTC day is refused until the next one.
+/// The query that crosses the byte limit is still served in full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct QuotaPolicy {
+    /// Queries per requester per day (None = unlimited)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queries: Option<u32>,
+    /// Bytes served to each requester per day (None = unlimited)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+}
+
+impl QuotaPolicy {
+    /// Limit each requester to `queries` queries a day.
+    pub fn with_queries(mut self, queries: u32) -> Self {
+        self.queries = Some(queries);
+        self
+    }
+
+    /// Limit each requester to `bytes` bytes served a day.
+    pub fn with_bytes(mut self, bytes: u64) -> Self {
+        self.bytes = Some(bytes);
+        self
+    }
+
+    /// Whether the policy limits nothing.
+    pub fn is_unlimited(&self) -> bool {
+        self.queries.is_none() && self.bytes.is_none()
+    }
+
+    /// The quota period (UTC day number) containing `at`.
+    pub fn period(at: Timestamp) -> u64 {
+        at / QUOTA_PERIOD_MS
+    }
+
+    /// When the quota period containing `at` ends and usage resets.
+    pub fn resets_at(at: Timestamp) -> Timestamp {
+        (Self::period(at) + 1) * QUOTA_PERIOD_MS
+    }
+}
+
+/// The limit of a [`QuotaPolicy`] a requester ran into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaLimit {
+    /// Daily queries
+    Queries,
+    /// Daily bytes served
+    Bytes,
+}
+
+impl std::fmt::Display for QuotaLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Queries => write!(f, "query"),
+            Self::Bytes => write!(f, "byte"),
+        }
+    }
 }
 
 /// Access control settings for content.
@@ -133,9 +466,12 @@ impl Metadata {
 ///
 /// # Access Logic
 /// Access granted if:
-/// - (allowlist is None OR peer in allowlist) AND
+/// - (allowlist and groups are None OR peer in allowlist OR peer is a
+///   member of one of the groups) AND
 /// - (denylist is None OR peer NOT in denylist) AND
-/// - (require_bond is false OR peer has posted bond)
+/// - (require_bond is false OR peer has posted bond) AND
+/// - (publish_at is None OR publish_at has passed) AND
+/// - (quota is None OR the peer has quota left today)
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct AccessControl {
@@ -149,6 +485,19 @@ pub struct AccessControl {
     pub bond_amount: Option<Amount>,
     /// Rate limit per peer (None = unlimited)
     pub max_queries_per_peer: Option<u32>,
+    /// Embargo: content is not announced or served before this time
+    /// (None = available immediately)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_at: Option<Timestamp>,
+    /// Members of these groups (by group ID) can query as if allowlisted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<Hash>>,
+    /// Free reads offered to each requester of paid content (None = none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trial: Option<TrialPolicy>,
+    /// Daily limits on each requester's queries (None = unlimited)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota: Option<QuotaPolicy>,
 }
 
 impl AccessControl {
@@ -173,13 +522,27 @@ impl AccessControl {
         }
     }
 
+    /// Create access control that admits the members of groups.
+    pub fn with_groups(groups: Vec<Hash>) -> Self {
+        Self {
+            groups: Some(groups),
+            ..Self::default()
+        }
+    }
+
     /// Check if a peer is allowed access based on these rules.
     ///
     /// Note: This does not check bond requirements, only list membership.
+    /// Group membership is not resolved here, so a peer admitted only
+    /// through a group is reported as not allowed.
     pub fn is_peer_allowed(&self, peer: &PeerId) -> bool {
         // Check allowlist
-        if let Some(ref allowlist) = self.allowlist {
-            if !allowlist.contains(peer) {
+        if self.allowlist.is_some() || self.groups.is_some() {
+            let listed = self
+                .allowlist
+                .as_ref()
+                .is_some_and(|allowlist| allowlist.contains(peer));
+            if !listed {
                 return false;
             }
         }
@@ -193,6 +556,16 @@ impl AccessControl {
 
         true
     }
+
+    /// Check if the content is still under embargo at `now`.
+    pub fn is_embargoed(&self, now: Timestamp) -> bool {
+        self.publish_at.is_some_and(|publish_at| now < publish_at)
+    }
+
+    /// Check if only specific peers (allowlisted or group members) can query.
+    pub fn is_restricted(&self) -> bool {
+        self.allowlist.is_some() || self.groups.is_some()
+    }
 }
 
 /// Economic parameters for content.
@@ -209,6 +582,16 @@ pub struct Economics {
     pub total_queries: u64,
     /// Total revenue generated
     pub total_revenue: Amount,
+    /// The publisher's bond when the content was published
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bond: Option<PublisherBond>,
+    /// Dynamic pricing rule; `price` is its floor. Fixed price when `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<PricingRule>,
+    /// Cut paid to discovery nodes that refer a paying query (None = no
+    /// referrals)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub referral: Option<ReferralPolicy>,
 }
 
 impl Default for Economics {
@@ -218,6 +601,9 @@ impl Default for Economics {
             currency: Currency::HBAR,
             total_queries: 0,
             total_revenue: 0,
+            bond: None,
+            pricing: None,
+            referral: None,
         }
     }
 }
@@ -230,6 +616,9 @@ impl Economics {
             currency: Currency::HBAR,
             total_queries: 0,
             total_revenue: 0,
+            bond: None,
+            pricing: None,
+            referral: None,
         }
     }
 
@@ -238,6 +627,153 @@ impl Economics {
         self.total_queries += 1;
         self.total_revenue += payment;
     }
+
+    /// Price charged at `now`, given the paid queries in the pricing rule's
+    /// demand window (ignored by other rules).
+    pub fn price_at(&self, now: Timestamp, window_queries: u64) -> Amount {
+        match &self.pricing {
+            Some(rule) => rule.price_at(self.price, now, window_queries),
+            None => self.price,
+        }
+    }
+}
+
+/// A rule moving the price of content within owner-set bounds.
+///
+/// The base price (`Economics::price`) is the floor. The price is a pure
+/// function of the time and, for surge pricing, the paid queries in the
+/// previous window, so a consumer quoted that count computes exactly what
+/// the owner charges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum PricingRule {
+    /// Each paid query in the previous window raises the price by
+    /// `step_bps` basis points of the base price, up to `max_price`.
+    Surge {
+        /// Highest price charged
+        max_price: Amount,
+        /// Length of a pricing window (ms)
+        window_ms: u64,
+        /// Increase per paid query, in basis points of the base price
+        step_bps: u32,
+    },
+    /// Dutch auction: the price falls linearly from `start_price` at
+    /// `starts_at` to the base price `duration_ms` later.
+    Decay {
+        /// Price at `starts_at`
+        start_price: Amount,
+        /// When the price starts falling
+        starts_at: Timestamp,
+        /// Time until the price reaches the base price (ms)
+        duration_ms: u64,
+    },
+}
+
+impl PricingRule {
+    /// Highest price the rule charges.
+    pub fn max_price(&self) -> Amount {
+        match self {
+            PricingRule::Surge { max_price, .. } => *max_price,
+            PricingRule::Decay { start_price, .. } => *start_price,
+        }
+    }
+
+    /// The window whose paid queries set the price at `now`: the whole
+    /// window before the one containing `now`. `None` for rules that don't
+    /// depend on queries.
+    pub fn demand_window(&self, now: Timestamp) -> Option<Range<Timestamp>> {
+        match self {
+            PricingRule::Surge { window_ms, .. } if *window_ms > 0 => {
+                let start = now - now % window_ms;
+                Some(start.saturating_sub(*window_ms)..start)
+            }
+            _ => None,
+        }
+    }
+
+    /// Price at `now` for a base price, given the paid queries in
+    /// `demand_window(now)`.
+    pub fn price_at(&self, base: Amount, now: Timestamp, window_queries: u64) -> Amount {
+        match *self {
+            PricingRule::Surge {
+                max_price,
+                step_bps,
+                ..
+            } => {
+                // The query count is quoted by the owner, so it may be huge
+                let increase = u128::from(base)
+                    .saturating_mul(u128::from(step_bps))
+                    .saturating_mul(u128::from(window_queries))
+                    / u128::from(BASIS_POINTS_DENOMINATOR);
+                let price = (u128::from(base) + increase).min(u128::from(max_price));
+                (price as Amount).max(base)
+            }
+            PricingRule::Decay {
+                start_price,
+                starts_at,
+                duration_ms,
+            } => {
+                let elapsed = now.saturating_sub(starts_at);
+                if start_price <= base || elapsed >= duration_ms {
+                    return base;
+                }
+                let drop =
+                    u128::from(start_price - base) * u128::from(elapsed) / u128::from(duration_ms);
+                start_price - drop as Amount
+            }
+        }
+    }
+}
+
+/// The cut of each payment an owner gives to whoever referred the query.
+///
+/// A search node or recommender that sends a paying consumer to content
+/// is named as the referrer in the query; its cut is taken off the top of
+/// the payment, before the protocol distribution, and settled with the
+/// other distributions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ReferralPolicy {
+    /// Referral cut in basis points of each payment (100 = 1%)
+    pub basis_points: u32,
+}
+
+impl ReferralPolicy {
+    /// Create a referral policy.
+    pub fn new(basis_points: u32) -> Self {
+        Self { basis_points }
+    }
+
+    /// The referrer's cut of a payment (rounded down).
+    pub fn amount(&self, payment: Amount) -> Amount {
+        (u128::from(payment) * u128::from(self.basis_points) / u128::from(BASIS_POINTS_DENOMINATOR))
+            as Amount
+    }
+}
+
+/// A bond posted on-chain by a publisher.
+///
+/// Publishers lock funds in the settlement contract as a stake against
+/// serving bad content. The manifest records the bond the publisher held
+/// when it was published; peers check the claim against the chain, and
+/// the bond can be slashed on proven fraud.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PublisherBond {
+    /// Total bonded amount (in tinybars)
+    pub amount: Amount,
+    /// Transaction that last added to the bond
+    pub transaction_id: String,
+}
+
+impl PublisherBond {
+    /// Create a new bond record.
+    pub fn new(amount: Amount, transaction_id: impl Into<String>) -> Self {
+        Self {
+            amount,
+            transaction_id: transaction_id.into(),
+        }
+    }
 }
 
 /// Complete manifest for a content item.
@@ -315,6 +851,33 @@ impl Manifest {
         }
     }
 
+    /// Check if content must be delivered encrypted to its recipient.
+    ///
+    /// Everything not openly shared is encrypted: restricted shared content,
+    /// and unlisted or private content served through a capability.
+    pub fn requires_encryption(&self) -> bool {
+        self.visibility != Visibility::Shared || self.access.is_restricted()
+    }
+
+    /// Price of one metered unit.
+    ///
+    /// The price is split evenly across the units, the last one taking the
+    /// remainder, so reading every unit costs exactly the content's price.
+    /// Returns `None` for content that isn't metered or an index past the
+    /// last unit.
+    pub fn unit_price(&self, index: u32) -> Option<Amount> {
+        let units = u64::from(self.metadata.metered_units());
+        if u64::from(index) >= units {
+            return None;
+        }
+        let share = self.economics.price / units;
+        if u64::from(index) + 1 == units {
+            Some(self.economics.price - share * (units - 1))
+        } else {
+            Some(share)
+        }
+    }
+
     /// Get the root hash (stable identifier across versions).
     pub fn root_hash(&self) -> Hash {
         self.version.root
@@ -392,6 +955,127 @@ mod tests {
         assert_eq!(metadata.tags.len(), 2);
         assert_eq!(metadata.content_size, 1024);
         assert_eq!(metadata.mime_type, Some("text/plain".to_string()));
+        assert!(metadata.schema.is_none());
+        assert!(metadata.fields.is_none());
+    }
+
+    #[test]
+    fn test_metadata_fields() {
+        let metadata = Metadata::new("Dataset", 1024)
+            .with_fields("nodalync:schema/dataset/v1", r#"{"rows":10}"#);
+        assert_eq!(
+            metadata.schema.as_deref(),
+            Some("nodalync:schema/dataset/v1")
+        );
+        assert_eq!(metadata.fields.as_deref(), Some(r#"{"rows":10}"#));
+
+        // Metadata without structured fields omits them when serialized
+        let json = serde_json::to_string(&Metadata::new("Plain", 1)).unwrap();
+        assert!(!json.contains("schema"));
+        let parsed: Metadata = serde_json::from_str(&json).unwrap();
+        assert!(parsed.schema.is_none());
+
+        let json = serde_json::to_string(&metadata).unwrap();
+        let parsed: Metadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, metadata);
+    }
+
+    #[test]
+    fn test_metadata_preview_policy() {
+        assert!(PreviewPolicy::new().is_empty());
+
+        let policy = PreviewPolicy::new()
+            .with_pattern(r"\d{3}-\d{2}-\d{4}")
+            .with_section("Appendix");
+        assert!(!policy.is_empty());
+
+        let metadata = Metadata::new("Report", 1024).with_preview_policy(policy.clone());
+        let json = serde_json::to_string(&metadata).unwrap();
+        let parsed: Metadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.preview_policy, Some(policy));
+
+        // Metadata without a policy omits it when serialized
+        let json = serde_json::to_string(&Metadata::new("Plain", 1)).unwrap();
+        assert!(!json.contains("preview_policy"));
+    }
+
+    #[test]
+    fn test_chunk_tree() {
+        let content: Vec<u8> = (0..10u8).collect();
+        let tree = ChunkTree::build(&content, 4);
+        assert_eq!(tree.len(), 3);
+        assert_eq!(ChunkTree::chunk_count(10, 4), 3);
+        assert_eq!(ChunkTree::chunk_count(8, 4), 2);
+        assert_eq!(tree.chunk_range(0, 10), Some(0..4));
+        assert_eq!(tree.chunk_range(2, 10), Some(8..10));
+        assert_eq!(tree.chunk_range(3, 10), None);
+
+        assert!(tree.verify_chunk(1, &content[4..8]));
+        assert!(tree.verify_chunk(2, &content[8..]));
+        // Right bytes at the wrong index, or tampered bytes, don't verify
+        assert!(!tree.verify_chunk(0, &content[4..8]));
+        assert!(!tree.verify_chunk(1, &[0u8; 4]));
+        assert!(!tree.verify_chunk(3, &[]));
+
+        // Only content larger than one chunk gets a tree
+        assert!(ChunkTree::for_content(b"small").is_none());
+        let large = vec![7u8; CHUNK_SIZE as usize + 1];
+        let tree = ChunkTree::for_content(&large).unwrap();
+        assert_eq!((tree.chunk_size, tree.len()), (CHUNK_SIZE, 2));
+
+        let metadata = Metadata {
+            chunks: Some(tree),
+            ..Metadata::new("Large", large.len() as u64)
+        };
+        let json = serde_json::to_string(&metadata).unwrap();
+        let parsed: Metadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, metadata);
+        let json = serde_json::to_string(&Metadata::new("Plain", 1)).unwrap();
+        assert!(!json.contains("chunks"));
+    }
+
+    #[test]
+    fn test_content_section() {
+        let content = b"# Intro\nhello\n# Data\nrows";
+        let section = ContentSection::new("Data", content, 14..content.len(), 50);
+        assert_eq!(
+            (section.offset, section.length, section.end()),
+            (14, 11, 25)
+        );
+        assert_eq!(section.range(), 14..25);
+        assert!(section.verify(&content[14..]));
+        // The same bytes at another offset, or tampered bytes, don't verify
+        assert!(!ContentSection::new("Data", content, 0..11, 50).verify(&content[14..]));
+        assert!(!section.verify(b"# Data\nrowz"));
+
+        let metadata = Metadata::new("Doc", content.len() as u64).with_sections(vec![section]);
+        assert_eq!(metadata.section(0).unwrap().title, "Data");
+        assert!(metadata.section(1).is_none());
+        let json = serde_json::to_string(&metadata).unwrap();
+        let parsed: Metadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, metadata);
+        let json = serde_json::to_string(&Metadata::new("Plain", 1)).unwrap();
+        assert!(!json.contains("sections"));
+    }
+
+    #[test]
+    fn test_unit_price() {
+        let content = vec![7u8; 2_500];
+        let mut manifest = Manifest::new_l0(
+            content_hash(&content),
+            test_peer_id(),
+            Metadata::new("Metered", content.len() as u64),
+            1_000,
+        );
+        manifest.economics.price = 100;
+        assert_eq!(manifest.metadata.metered_units(), 0);
+        assert_eq!(manifest.unit_price(0), None);
+
+        manifest.metadata.metering = Some(ChunkTree::build(&content, 1_000));
+        assert_eq!(manifest.metadata.metered_units(), 3);
+        let prices: Vec<_> = (0..3).map(|i| manifest.unit_price(i).unwrap()).collect();
+        assert_eq!(prices, vec![33, 33, 34]);
+        assert_eq!(manifest.unit_price(3), None);
     }
 
     #[test]
@@ -424,6 +1108,88 @@ mod tests {
         assert!(access.is_peer_allowed(&other_peer));
     }
 
+    #[test]
+    fn test_access_control_groups() {
+        let listed_peer = test_peer_id();
+        let mut access = AccessControl::with_groups(vec![Hash([1u8; 32])]);
+
+        // Groups are not resolved here, so only listed peers pass
+        assert!(!access.is_peer_allowed(&listed_peer));
+        access.allowlist = Some(vec![listed_peer]);
+        assert!(access.is_peer_allowed(&listed_peer));
+        assert!(access.is_restricted());
+        assert!(!AccessControl::with_denylist(vec![listed_peer]).is_restricted());
+
+        // Omitted from serialized manifests when unset
+        let json = serde_json::to_string(&AccessControl::open()).unwrap();
+        assert!(!json.contains("groups"));
+        let json = serde_json::to_string(&access).unwrap();
+        let parsed: AccessControl = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, access);
+    }
+
+    #[test]
+    fn test_access_control_embargo() {
+        let mut access = AccessControl::open();
+        assert!(!access.is_embargoed(1000));
+
+        access.publish_at = Some(2000);
+        assert!(access.is_embargoed(1999));
+        assert!(!access.is_embargoed(2000));
+
+        // Unset embargo is omitted on the wire
+        let json = serde_json::to_string(&AccessControl::open()).unwrap();
+        assert!(!json.contains("publish_at"));
+        let deserialized: AccessControl = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.publish_at, None);
+    }
+
+    #[test]
+    fn test_access_control_trial() {
+        let trial = TrialPolicy::new(3).with_bytes(4_096);
+        assert_eq!(trial.reads_left(1), 2);
+        assert_eq!(trial.reads_left(5), 0);
+
+        // Unset trial is omitted on the wire
+        let json = serde_json::to_string(&AccessControl::open()).unwrap();
+        assert!(!json.contains("trial"));
+
+        let access = AccessControl {
+            trial: Some(trial),
+            ..AccessControl::default()
+        };
+        let json = serde_json::to_string(&access).unwrap();
+        let parsed: AccessControl = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, access);
+    }
+
+    #[test]
+    fn test_access_control_quota() {
+        let quota = QuotaPolicy::default().with_queries(10).with_bytes(1_024);
+        assert!(!quota.is_unlimited());
+        assert!(QuotaPolicy::default().is_unlimited());
+
+        // Periods are UTC days
+        assert_eq!(QuotaPolicy::period(QUOTA_PERIOD_MS - 1), 0);
+        assert_eq!(QuotaPolicy::period(QUOTA_PERIOD_MS), 1);
+        assert_eq!(
+            QuotaPolicy::resets_at(QUOTA_PERIOD_MS + 5),
+            2 * QUOTA_PERIOD_MS
+        );
+
+        // Unset quota is omitted on the wire
+        let json = serde_json::to_string(&AccessControl::open()).unwrap();
+        assert!(!json.contains("quota"));
+
+        let access = AccessControl {
+            quota: Some(quota),
+            ..AccessControl::default()
+        };
+        let json = serde_json::to_string(&access).unwrap();
+        let parsed: AccessControl = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, access);
+    }
+
     #[test]
     fn test_economics() {
         let mut economics = Economics::with_price(100);
@@ -441,6 +1207,101 @@ mod tests {
         assert_eq!(economics.total_revenue, 200);
     }
 
+    #[test]
+    fn test_economics_bond_serde() {
+        let mut economics = Economics::with_price(100);
+        let json = serde_json::to_string(&economics).unwrap();
+        assert!(!json.contains("bond"));
+
+        economics.bond = Some(PublisherBond::new(5_000, "0.0.1@123.456"));
+        let json = serde_json::to_string(&economics).unwrap();
+        let deserialized: Economics = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, economics);
+    }
+
+    #[test]
+    fn test_referral_policy() {
+        let mut economics = Economics::with_price(1_000);
+        let json = serde_json::to_string(&economics).unwrap();
+        assert!(!json.contains("referral"));
+
+        let policy = ReferralPolicy::new(1_500);
+        assert_eq!(policy.amount(1_000), 150);
+        assert_eq!(policy.amount(9), 1);
+        economics.referral = Some(policy);
+        let json = serde_json::to_string(&economics).unwrap();
+        let deserialized: Economics = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, economics);
+    }
+
+    #[test]
+    fn test_surge_pricing() {
+        let rule = PricingRule::Surge {
+            max_price: 250,
+            window_ms: 3_600_000,
+            step_bps: 1_000,
+        };
+        assert_eq!(rule.price_at(100, 0, 0), 100);
+        assert_eq!(rule.price_at(100, 0, 5), 150);
+        assert_eq!(rule.price_at(100, 0, 100), 250);
+        assert_eq!(rule.max_price(), 250);
+
+        // Extreme inputs saturate at the highest price
+        let steep = PricingRule::Surge {
+            max_price: Amount::MAX,
+            window_ms: 3_600_000,
+            step_bps: u32::MAX,
+        };
+        assert_eq!(steep.price_at(Amount::MAX, 0, u64::MAX), Amount::MAX);
+        assert_eq!(rule.price_at(Amount::MAX, 0, u64::MAX), Amount::MAX);
+
+        // The previous full window counts
+        assert_eq!(rule.demand_window(7_500_000), Some(3_600_000..7_200_000));
+        assert_eq!(rule.demand_window(1_000), Some(0..0));
+
+        let mut economics = Economics::with_price(100);
+        assert_eq!(economics.price_at(0, 5), 100);
+        economics.pricing = Some(rule);
+        assert_eq!(economics.price_at(0, 5), 150);
+    }
+
+    #[test]
+    fn test_decay_pricing() {
+        let rule = PricingRule::Decay {
+            start_price: 1_000,
+            starts_at: 10_000,
+            duration_ms: 1_000,
+        };
+        assert_eq!(rule.price_at(100, 5_000, 0), 1_000);
+        assert_eq!(rule.price_at(100, 10_000, 0), 1_000);
+        assert_eq!(rule.price_at(100, 10_500, 0), 550);
+        assert_eq!(rule.price_at(100, 10_999, 0), 101);
+        assert_eq!(rule.price_at(100, 11_000, 0), 100);
+        assert_eq!(rule.price_at(100, 50_000, 0), 100);
+        assert_eq!(rule.demand_window(10_500), None);
+
+        // Never below the base price
+        assert_eq!(rule.price_at(2_000, 10_000, 0), 2_000);
+    }
+
+    #[test]
+    fn test_pricing_rule_serde() {
+        let mut economics = Economics::with_price(100);
+        assert!(!serde_json::to_string(&economics)
+            .unwrap()
+            .contains("pricing"));
+
+        economics.pricing = Some(PricingRule::Decay {
+            start_price: 1_000,
+            starts_at: 10_000,
+            duration_ms: 1_000,
+        });
+        let json = serde_json::to_string(&economics).unwrap();
+        assert!(json.contains(r#""mode":"decay""#));
+        let deserialized: Economics = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, economics);
+    }
+
     #[test]
     fn test_manifest_new_l0() {
         let hash = test_hash();
@@ -489,6 +1350,24 @@ mod tests {
         assert!(!manifest.is_queryable_by(&other));
     }
 
+    #[test]
+    fn test_manifest_requires_encryption() {
+        let other = test_peer_id();
+        let metadata = Metadata::new("Test", 100);
+        let mut manifest = Manifest::new_l0(test_hash(), test_peer_id(), metadata, 1000);
+
+        // Only openly shared content travels in plaintext
+        assert!(manifest.requires_encryption());
+        manifest.visibility = Visibility::Unlisted;
+        assert!(manifest.requires_encryption());
+        manifest.visibility = Visibility::Shared;
+        assert!(!manifest.requires_encryption());
+        manifest.access = AccessControl::with_denylist(vec![other]);
+        assert!(!manifest.requires_encryption());
+        manifest.access = AccessControl::with_allowlist(vec![other]);
+        assert!(manifest.requires_encryption());
+    }
+
     #[test]
     fn test_manifest_serialization() {
         let hash = test_hash();
@@ -76,6 +76,10 @@ pub struct Provenance {
     pub derived_from: Vec<Hash>,
     /// Max derivation depth from any L0
     pub depth: u32,
+    /// Whether the owner claims an on-chain attestation of the content
+    /// with this chain's provenance root (spec §12.2)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub attested: bool,
 }
 
 impl Provenance {
@@ -87,6 +91,7 @@ impl Provenance {
             root_l0l1: vec![ProvenanceEntry::self_reference(hash, owner)],
             derived_from: Vec::new(),
             depth: 0,
+            attested: false,
         }
     }
 
@@ -98,6 +103,7 @@ impl Provenance {
             root_l0l1: sources,
             derived_from,
             depth,
+            attested: false,
         }
     }
 
@@ -147,11 +153,26 @@ impl Provenance {
     /// This merges all root_L0L1 entries from the sources, handling duplicates
     /// by accumulating weights, and computes the new depth.
     pub fn from_sources(sources: &[(Hash, &Provenance, PeerId, Visibility)]) -> Self {
+        let weighted: Vec<_> = sources
+            .iter()
+            .map(|(hash, provenance, owner, visibility)| {
+                (*hash, *provenance, *owner, *visibility, 1)
+            })
+            .collect();
+        Self::from_weighted_sources(&weighted)
+    }
+
+    /// Create provenance from sources that each carry a weight.
+    ///
+    /// Like `from_sources`, but every root entry contributed by a source has
+    /// its weight multiplied by that source's weight. Used for collections,
+    /// where item weights decide how bundle revenue is split.
+    pub fn from_weighted_sources(sources: &[(Hash, &Provenance, PeerId, Visibility, u32)]) -> Self {
         let mut all_entries = Vec::new();
         let mut derived_from = Vec::new();
         let mut max_depth = 0u32;
 
-        for (hash, provenance, owner, visibility) in sources {
+        for (hash, provenance, owner, visibility, weight) in sources {
             // Add this source to derived_from
             derived_from.push(*hash);
 
@@ -161,7 +182,7 @@ impl Provenance {
                     hash: entry.hash,
                     owner: entry.owner,
                     visibility: entry.visibility,
-                    weight: entry.weight,
+                    weight: entry.weight.saturating_mul(*weight),
                 });
             }
 
@@ -170,7 +191,12 @@ impl Provenance {
 
             // If the source itself is L0, ensure it's in root entries
             if provenance.is_l0() {
-                all_entries.push(ProvenanceEntry::new(*hash, *owner, *visibility));
+                all_entries.push(ProvenanceEntry::with_weight(
+                    *hash,
+                    *owner,
+                    *visibility,
+                    *weight,
+                ));
             }
         }
 
@@ -181,6 +207,7 @@ impl Provenance {
             root_l0l1: merged,
             derived_from,
             depth: max_depth + 1,
+            attested: false,
         }
     }
 
@@ -381,6 +408,35 @@ mod tests {
         assert!(provenance.root_l0l1.len() >= 2);
     }
 
+    #[test]
+    fn test_provenance_from_weighted_sources() {
+        let source1_hash = test_hash(b"source1");
+        let source2_hash = test_hash(b"source2");
+        let owner1 = test_peer_id();
+        let owner2 = test_peer_id();
+
+        let prov1 = Provenance::new_l0(source1_hash, owner1);
+        let prov2 = Provenance::new_l0(source2_hash, owner2);
+
+        let provenance = Provenance::from_weighted_sources(&[
+            (source1_hash, &prov1, owner1, Visibility::Shared, 1),
+            (source2_hash, &prov2, owner2, Visibility::Shared, 3),
+        ]);
+
+        assert_eq!(provenance.depth, 1);
+        assert_eq!(provenance.derived_from, vec![source1_hash, source2_hash]);
+        let weight = |hash| {
+            provenance
+                .root_l0l1
+                .iter()
+                .find(|e| e.hash == hash)
+                .unwrap()
+                .weight
+        };
+        // Source 2 carries three times the weight of source 1
+        assert_eq!(weight(source2_hash), 3 * weight(source1_hash));
+    }
+
     #[test]
     fn test_provenance_serialization() {
         let hash = test_hash(b"content");
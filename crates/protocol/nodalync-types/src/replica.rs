@@ -0,0 +1,142 @@
+//! Signed delegations letting another node serve a publisher's catalog.
+//!
+//! A publisher (the primary) can run several serving nodes for one catalog.
+//! Each replica holds a delegation signed by the primary naming it. The
+//! replica imports the primary's manifests and content, serves previews
+//! and queries for them, and announces them with the delegation attached,
+//! so peers attribute the content to the primary rather than the replica.
+//! Payments for replicated content are distributed to the primary as the
+//! content owner; the delegation names the primary's settlement account so
+//! the replica can settle to it.
+
+use nodalync_crypto::{content_hash, Hash, PeerId, PublicKey, Signature, Timestamp};
+use serde::{Deserialize, Serialize};
+
+/// A primary's signed permission for a replica to serve its catalog.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ReplicaDelegation {
+    /// Hash of the delegation terms (see [`ReplicaDelegation::compute_hash`])
+    pub hash: Hash,
+    /// Publisher whose catalog is served
+    pub primary: PeerId,
+    /// Primary's public key, for signature verification
+    pub primary_key: PublicKey,
+    /// Node allowed to serve the catalog
+    pub replica: PeerId,
+    /// Primary's settlement account (e.g. `0.0.12345`), if it has one
+    pub settlement_account: Option<String>,
+    /// When the delegation was issued
+    pub issued_at: Timestamp,
+    /// When the delegation expires
+    pub expires_at: Timestamp,
+    /// Primary's signature over `hash`
+    pub signature: Signature,
+}
+
+impl ReplicaDelegation {
+    /// Create an unsigned delegation.
+    ///
+    /// The hash is computed from the terms; the signature is left zeroed
+    /// until the primary signs it.
+    pub fn new(
+        primary: PeerId,
+        primary_key: PublicKey,
+        replica: PeerId,
+        settlement_account: Option<String>,
+        issued_at: Timestamp,
+        expires_at: Timestamp,
+    ) -> Self {
+        let mut delegation = Self {
+            hash: Hash([0u8; 32]),
+            primary,
+            primary_key,
+            replica,
+            settlement_account,
+            issued_at,
+            expires_at,
+            signature: Signature::from_bytes([0u8; 64]),
+        };
+        delegation.hash = delegation.compute_hash();
+        delegation
+    }
+
+    /// Compute the hash of the delegation terms.
+    ///
+    /// Covers every field except `hash` and `signature`, prefixed with a
+    /// domain tag.
+    pub fn compute_hash(&self) -> Hash {
+        let mut data = Vec::with_capacity(128);
+        data.extend_from_slice(b"nodalync:replica");
+        data.extend_from_slice(&self.primary.0);
+        data.extend_from_slice(&self.primary_key.0);
+        data.extend_from_slice(&self.replica.0);
+        if let Some(account) = &self.settlement_account {
+            data.push(1);
+            data.extend_from_slice(&(account.len() as u32).to_be_bytes());
+            data.extend_from_slice(account.as_bytes());
+        } else {
+            data.push(0);
+        }
+        data.extend_from_slice(&self.issued_at.to_be_bytes());
+        data.extend_from_slice(&self.expires_at.to_be_bytes());
+        content_hash(&data)
+    }
+
+    /// Check if the delegation has expired at the given time.
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        now >= self.expires_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+
+    fn test_delegation() -> ReplicaDelegation {
+        let (_, public_key) = generate_identity();
+        ReplicaDelegation::new(
+            peer_id_from_public_key(&public_key),
+            public_key,
+            PeerId::from_bytes([2u8; 20]),
+            Some("0.0.12345".to_string()),
+            1_000,
+            2_000,
+        )
+    }
+
+    #[test]
+    fn test_replica_delegation_hash_covers_terms() {
+        let delegation = test_delegation();
+        assert_eq!(delegation.hash, delegation.compute_hash());
+
+        let mut changed = delegation.clone();
+        changed.replica = PeerId::from_bytes([3u8; 20]);
+        assert_ne!(changed.compute_hash(), delegation.hash);
+
+        let mut changed = delegation.clone();
+        changed.settlement_account = None;
+        assert_ne!(changed.compute_hash(), delegation.hash);
+
+        let mut changed = delegation.clone();
+        changed.expires_at += 1;
+        assert_ne!(changed.compute_hash(), delegation.hash);
+
+        // The signature is not part of the terms
+        let mut signed = delegation.clone();
+        signed.signature = Signature::from_bytes([1u8; 64]);
+        assert_eq!(signed.compute_hash(), delegation.hash);
+    }
+
+    #[test]
+    fn test_replica_delegation_expiry() {
+        let delegation = test_delegation();
+        assert!(!delegation.is_expired(1_999));
+        assert!(delegation.is_expired(2_000));
+
+        let json = serde_json::to_string(&delegation).unwrap();
+        let parsed: ReplicaDelegation = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, delegation);
+    }
+}
@@ -0,0 +1,196 @@
+//! Signed content reports for moderation.
+//!
+//! Any peer can report a piece of content it considers spam, abusive or
+//! otherwise objectionable. Reports are signed by the reporter and
+//! broadcast; each node decides for itself what to do with them according
+//! to its moderation policy. Reports carry the reporter's public key so
+//! they can be verified without a key lookup.
+
+use nodalync_crypto::{content_hash, Hash, PeerId, PublicKey, Signature, Timestamp};
+use serde::{Deserialize, Serialize};
+
+/// Maximum length of a report comment (characters)
+pub const MAX_REPORT_COMMENT_LENGTH: usize = 500;
+
+/// Why content was reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ReportReason {
+    /// Unsolicited or misleading content
+    Spam,
+    /// Harassment or abusive content
+    Abuse,
+    /// Infringes someone's copyright
+    Copyright,
+    /// Illegal content
+    Illegal,
+    /// Anything else; see the comment
+    Other,
+}
+
+impl ReportReason {
+    /// Get the string representation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportReason::Spam => "spam",
+            ReportReason::Abuse => "abuse",
+            ReportReason::Copyright => "copyright",
+            ReportReason::Illegal => "illegal",
+            ReportReason::Other => "other",
+        }
+    }
+
+    /// Parse from the string representation.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "spam" => Some(ReportReason::Spam),
+            "abuse" => Some(ReportReason::Abuse),
+            "copyright" => Some(ReportReason::Copyright),
+            "illegal" => Some(ReportReason::Illegal),
+            "other" => Some(ReportReason::Other),
+            _ => None,
+        }
+    }
+
+    /// Byte tag used when hashing report terms.
+    fn tag(&self) -> u8 {
+        match self {
+            ReportReason::Spam => 0x00,
+            ReportReason::Abuse => 0x01,
+            ReportReason::Copyright => 0x02,
+            ReportReason::Illegal => 0x03,
+            ReportReason::Other => 0x04,
+        }
+    }
+}
+
+impl std::fmt::Display for ReportReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A peer's signed report about one piece of content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ContentReport {
+    /// Hash of the report terms (see [`ContentReport::compute_hash`])
+    pub hash: Hash,
+    /// Content being reported
+    pub content_hash: Hash,
+    /// Peer filing the report
+    pub reporter: PeerId,
+    /// Reporter's public key, for signature verification
+    pub reporter_key: PublicKey,
+    /// Why the content was reported
+    pub reason: ReportReason,
+    /// Free-form explanation (may be empty)
+    pub comment: String,
+    /// When the report was filed
+    pub created_at: Timestamp,
+    /// Reporter's signature over `hash`
+    pub signature: Signature,
+}
+
+impl ContentReport {
+    /// Create an unsigned report.
+    ///
+    /// The hash is computed from the terms; the signature is left zeroed
+    /// until the reporter signs it.
+    pub fn new(
+        content_hash: Hash,
+        reporter: PeerId,
+        reporter_key: PublicKey,
+        reason: ReportReason,
+        comment: impl Into<String>,
+        created_at: Timestamp,
+    ) -> Self {
+        let mut report = Self {
+            hash: Hash([0u8; 32]),
+            content_hash,
+            reporter,
+            reporter_key,
+            reason,
+            comment: comment.into(),
+            created_at,
+            signature: Signature::from_bytes([0u8; 64]),
+        };
+        report.hash = report.compute_hash();
+        report
+    }
+
+    /// Compute the hash of the report terms.
+    ///
+    /// Covers every field except `hash` and `signature`.
+    pub fn compute_hash(&self) -> Hash {
+        let mut data = Vec::with_capacity(105 + self.comment.len());
+        data.extend_from_slice(&self.content_hash.0);
+        data.extend_from_slice(&self.reporter.0);
+        data.extend_from_slice(&self.reporter_key.0);
+        data.push(self.reason.tag());
+        data.extend_from_slice(&self.created_at.to_be_bytes());
+        data.extend_from_slice(self.comment.as_bytes());
+        content_hash(&data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+
+    fn test_report() -> ContentReport {
+        let (_, public_key) = generate_identity();
+        ContentReport::new(
+            content_hash(b"reported notes"),
+            peer_id_from_public_key(&public_key),
+            public_key,
+            ReportReason::Spam,
+            "Link farm",
+            1_000,
+        )
+    }
+
+    #[test]
+    fn test_report_hash_covers_terms() {
+        let report = test_report();
+        assert_eq!(report.hash, report.compute_hash());
+
+        let mut changed = report.clone();
+        changed.reason = ReportReason::Abuse;
+        assert_ne!(changed.compute_hash(), report.hash);
+
+        let mut changed = report.clone();
+        changed.comment = "Something else".to_string();
+        assert_ne!(changed.compute_hash(), report.hash);
+
+        let mut changed = report.clone();
+        changed.content_hash = content_hash(b"other notes");
+        assert_ne!(changed.compute_hash(), report.hash);
+
+        // The signature is not part of the terms
+        let mut signed = report.clone();
+        signed.signature = Signature::from_bytes([1u8; 64]);
+        assert_eq!(signed.compute_hash(), report.hash);
+    }
+
+    #[test]
+    fn test_report_reason_strings() {
+        for reason in [
+            ReportReason::Spam,
+            ReportReason::Abuse,
+            ReportReason::Copyright,
+            ReportReason::Illegal,
+            ReportReason::Other,
+        ] {
+            assert_eq!(ReportReason::parse(reason.as_str()), Some(reason));
+        }
+        assert_eq!(ReportReason::parse("boring"), None);
+
+        let json = serde_json::to_string(&test_report()).unwrap();
+        assert!(json.contains("\"reason\":\"spam\""));
+        let parsed: ContentReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.reason, ReportReason::Spam);
+    }
+}
@@ -0,0 +1,105 @@
+//! Tag taxonomy.
+//!
+//! Tags are hierarchical: segments are separated by [`TAG_SEPARATOR`], so
+//! `science/biology` is a child of `science`. Content tagged with a child tag
+//! is also filed under each of its ancestors.
+//!
+//! Tags are normalized before they are stored or compared, so `Science /
+//! Biology` and `science/biology` are the same tag.
+
+/// Separator between the segments of a hierarchical tag.
+pub const TAG_SEPARATOR: char = '/';
+
+/// Normalize a tag.
+///
+/// Lowercases the tag, trims each segment, collapses runs of whitespace to a
+/// single space and drops empty segments. Returns `None` if nothing is left.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let segments: Vec<String> = tag
+        .split(TAG_SEPARATOR)
+        .map(|segment| {
+            segment
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase()
+        })
+        .filter(|segment| !segment.is_empty())
+        .collect();
+
+    if segments.is_empty() {
+        None
+    } else {
+        Some(segments.join(&TAG_SEPARATOR.to_string()))
+    }
+}
+
+/// Normalize a list of tags.
+///
+/// Empty tags and duplicates are dropped; the first occurrence of each tag
+/// keeps its position.
+pub fn normalize_tags<S: AsRef<str>>(tags: &[S]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags.iter().filter_map(|t| normalize_tag(t.as_ref())) {
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+/// Get the parent of a normalized tag, or `None` for a top-level tag.
+pub fn tag_parent(tag: &str) -> Option<&str> {
+    tag.rfind(TAG_SEPARATOR).map(|i| &tag[..i])
+}
+
+/// Get a normalized tag and its ancestors, most general first.
+///
+/// `science/biology/genetics` gives `science`, `science/biology` and
+/// `science/biology/genetics`.
+pub fn tag_path(tag: &str) -> Vec<&str> {
+    let mut path: Vec<&str> = tag
+        .match_indices(TAG_SEPARATOR)
+        .map(|(i, _)| &tag[..i])
+        .collect();
+    path.push(tag);
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tag() {
+        assert_eq!(
+            normalize_tag(" Science / Molecular   Biology "),
+            Some("science/molecular biology".to_string())
+        );
+        assert_eq!(normalize_tag("rust"), Some("rust".to_string()));
+        assert_eq!(normalize_tag("a//b/"), Some("a/b".to_string()));
+        assert_eq!(normalize_tag(" / "), None);
+        assert_eq!(normalize_tag(""), None);
+    }
+
+    #[test]
+    fn test_normalize_tags_dedupes() {
+        let tags = normalize_tags(&["Rust", "", "science/Biology", "rust ", "Science/biology"]);
+        assert_eq!(tags, vec!["rust", "science/biology"]);
+    }
+
+    #[test]
+    fn test_tag_hierarchy() {
+        assert_eq!(
+            tag_parent("science/biology/genetics"),
+            Some("science/biology")
+        );
+        assert_eq!(tag_parent("science"), None);
+
+        assert_eq!(
+            tag_path("science/biology/genetics"),
+            vec!["science", "science/biology", "science/biology/genetics"]
+        );
+        assert_eq!(tag_path("science"), vec!["science"]);
+    }
+}
@@ -0,0 +1,112 @@
+//! Signed tombstones withdrawing content from the network.
+//!
+//! When an owner unpublishes or deletes content, it signs a tombstone for
+//! the content hash and broadcasts it. Peers that accept it drop cached
+//! copies, stop listing the announcement, and refuse to serve the hash.
+//! Only the content owner's tombstone counts, so nobody else can withdraw
+//! content they don't own. Like capability tokens, the tombstone carries
+//! the owner's public key so it can be verified without a key lookup.
+
+use nodalync_crypto::{content_hash, Hash, PeerId, PublicKey, Signature, Timestamp};
+use serde::{Deserialize, Serialize};
+
+/// An owner's signed withdrawal of one piece of content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Tombstone {
+    /// Hash of the tombstone terms (see [`Tombstone::compute_hash`])
+    pub hash: Hash,
+    /// Content being withdrawn
+    pub content_hash: Hash,
+    /// Content owner withdrawing it
+    pub owner: PeerId,
+    /// Owner's public key, for signature verification
+    pub owner_key: PublicKey,
+    /// When the content was withdrawn
+    pub created_at: Timestamp,
+    /// Owner's signature over `hash`
+    pub signature: Signature,
+}
+
+impl Tombstone {
+    /// Create an unsigned tombstone.
+    ///
+    /// The hash is computed from the terms; the signature is left zeroed
+    /// until the owner signs it.
+    pub fn new(
+        content_hash: Hash,
+        owner: PeerId,
+        owner_key: PublicKey,
+        created_at: Timestamp,
+    ) -> Self {
+        let mut tombstone = Self {
+            hash: Hash([0u8; 32]),
+            content_hash,
+            owner,
+            owner_key,
+            created_at,
+            signature: Signature::from_bytes([0u8; 64]),
+        };
+        tombstone.hash = tombstone.compute_hash();
+        tombstone
+    }
+
+    /// Compute the hash of the tombstone terms.
+    ///
+    /// Covers every field except `hash` and `signature`.
+    pub fn compute_hash(&self) -> Hash {
+        let mut data = Vec::with_capacity(96);
+        data.extend_from_slice(&self.content_hash.0);
+        data.extend_from_slice(&self.owner.0);
+        data.extend_from_slice(&self.owner_key.0);
+        data.extend_from_slice(&self.created_at.to_be_bytes());
+        content_hash(&data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{generate_identity, peer_id_from_public_key};
+
+    fn test_tombstone() -> Tombstone {
+        let (_, public_key) = generate_identity();
+        Tombstone::new(
+            content_hash(b"withdrawn notes"),
+            peer_id_from_public_key(&public_key),
+            public_key,
+            1_000,
+        )
+    }
+
+    #[test]
+    fn test_tombstone_hash_covers_terms() {
+        let tombstone = test_tombstone();
+        assert_eq!(tombstone.hash, tombstone.compute_hash());
+
+        let mut changed = tombstone.clone();
+        changed.content_hash = content_hash(b"other notes");
+        assert_ne!(changed.compute_hash(), tombstone.hash);
+
+        let mut changed = tombstone.clone();
+        changed.owner = PeerId([7u8; 20]);
+        assert_ne!(changed.compute_hash(), tombstone.hash);
+
+        let mut changed = tombstone.clone();
+        changed.created_at += 1;
+        assert_ne!(changed.compute_hash(), tombstone.hash);
+
+        // The signature is not part of the terms
+        let mut signed = tombstone.clone();
+        signed.signature = Signature::from_bytes([1u8; 64]);
+        assert_eq!(signed.compute_hash(), tombstone.hash);
+    }
+
+    #[test]
+    fn test_tombstone_serialization() {
+        let tombstone = test_tombstone();
+        let json = serde_json::to_string(&tombstone).unwrap();
+        let parsed: Tombstone = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, tombstone);
+    }
+}
@@ -459,6 +459,7 @@ fn appendix_c_query_errors() {
     assert_eq!(ErrorCode::PaymentInvalid as u16, 0x0004);
     assert_eq!(ErrorCode::RateLimited as u16, 0x0005);
     assert_eq!(ErrorCode::VersionNotFound as u16, 0x0006);
+    assert_eq!(ErrorCode::ChallengeRequired as u16, 0x0007);
 }
 
 #[test]
@@ -3,11 +3,18 @@
 //! This module validates access permissions:
 //! - Visibility checks (Private, Unlisted, Shared)
 //! - Allowlist/denylist enforcement
+//! - Group membership (through a [`GroupResolver`])
 //! - Bond requirements
+//! - Publish embargoes
+//! - Free trial reads of paid content
+//! - Daily quotas per requester
 
-use nodalync_types::{Manifest, PeerId, Visibility};
+use nodalync_types::{
+    Manifest, PeerId, QuotaLimit, QuotaPolicy, Timestamp, TrialPolicy, Visibility, MAX_TRIAL_READS,
+};
 
 use crate::error::{ValidationError, ValidationResult};
+use crate::group::GroupResolver;
 use crate::payment::BondChecker;
 
 /// Validate access for a requester to content.
@@ -32,6 +39,20 @@ pub fn validate_access(
     requester: &PeerId,
     manifest: &Manifest,
     bond_checker: Option<&dyn BondChecker>,
+) -> ValidationResult<()> {
+    validate_access_with_groups(requester, manifest, bond_checker, None)
+}
+
+/// Validate access for a requester to content, resolving groups.
+///
+/// Like [`validate_access`], but for Unlisted content a requester who is a
+/// member of one of `access.groups` passes as if allowlisted. Without a
+/// `group_resolver`, no one is a member of any group.
+pub fn validate_access_with_groups(
+    requester: &PeerId,
+    manifest: &Manifest,
+    bond_checker: Option<&dyn BondChecker>,
+    group_resolver: Option<&dyn GroupResolver>,
 ) -> ValidationResult<()> {
     // Check visibility rules
     match manifest.visibility {
@@ -40,11 +61,9 @@ pub fn validate_access(
             return Err(ValidationError::ContentPrivate);
         }
         Visibility::Unlisted => {
-            // Check allowlist if set
-            if let Some(ref allowlist) = manifest.access.allowlist {
-                if !allowlist.contains(requester) {
-                    return Err(ValidationError::NotInAllowlist);
-                }
+            // Check allowlist and groups if set
+            if !is_listed(requester, manifest, group_resolver) {
+                return Err(ValidationError::NotInAllowlist);
             }
             // Check denylist if set
             if let Some(ref denylist) = manifest.access.denylist {
@@ -89,6 +108,34 @@ pub fn validate_access(
     Ok(())
 }
 
+/// Check the requester against the allowlist and groups.
+///
+/// Passes if neither is set, the requester is allowlisted, or it is a
+/// member of one of the groups.
+fn is_listed(
+    requester: &PeerId,
+    manifest: &Manifest,
+    group_resolver: Option<&dyn GroupResolver>,
+) -> bool {
+    let access = &manifest.access;
+    if access.allowlist.is_none() && access.groups.is_none() {
+        return true;
+    }
+    if access
+        .allowlist
+        .as_ref()
+        .is_some_and(|allowlist| allowlist.contains(requester))
+    {
+        return true;
+    }
+    match (&access.groups, group_resolver) {
+        (Some(groups), Some(resolver)) => groups
+            .iter()
+            .any(|group| resolver.is_member(group, requester)),
+        _ => false,
+    }
+}
+
 /// Validate access without bond checking.
 ///
 /// Use when bond checking is not required or bonds are checked separately.
@@ -96,6 +143,98 @@ pub fn validate_access_basic(requester: &PeerId, manifest: &Manifest) -> Validat
     validate_access(requester, manifest, None)
 }
 
+/// Validate that content is past its scheduled publish time.
+///
+/// Content with `access.publish_at` set may not be served before that time,
+/// regardless of visibility. Owners are not exempt here; use
+/// `is_owner` before calling if they should be.
+pub fn validate_embargo(manifest: &Manifest, now: Timestamp) -> ValidationResult<()> {
+    match manifest.access.publish_at {
+        Some(publish_at) if now < publish_at => Err(ValidationError::Embargoed { publish_at }),
+        _ => Ok(()),
+    }
+}
+
+/// Validate a free trial read of paid content.
+///
+/// Passes if the content offers a trial and the requester, having already
+/// used `used` free reads of it, has any left. A trial stands in for the
+/// payment only; run the usual access checks first.
+pub fn validate_trial(manifest: &Manifest, used: u32) -> ValidationResult<()> {
+    match manifest.access.trial {
+        Some(trial) if trial.reads_left(used) > 0 => Ok(()),
+        Some(trial) => Err(ValidationError::TrialExhausted {
+            allowed: trial.reads,
+        }),
+        None => Err(ValidationError::TrialExhausted { allowed: 0 }),
+    }
+}
+
+/// Validate a trial policy an owner sets on content.
+///
+/// The policy must offer between one and `MAX_TRIAL_READS` reads, and a
+/// non-zero number of bytes if it limits them.
+pub fn validate_trial_policy(policy: &TrialPolicy) -> ValidationResult<()> {
+    if policy.reads == 0 || policy.reads > MAX_TRIAL_READS {
+        return Err(ValidationError::InvalidTrial {
+            reason: format!("reads must be between 1 and {}", MAX_TRIAL_READS),
+        });
+    }
+    if policy.bytes == Some(0) {
+        return Err(ValidationError::InvalidTrial {
+            reason: "free bytes must be non-zero".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Validate a query against a daily quota.
+///
+/// `queries` and `bytes` are what the requester has already used in the
+/// UTC day containing `now`, and `size` the bytes the query would be
+/// served. Fails with `QuotaExceeded`, carrying the start of the next day,
+/// if the query would take the requester past either limit.
+pub fn validate_quota(
+    policy: &QuotaPolicy,
+    queries: u32,
+    bytes: u64,
+    size: u64,
+    now: Timestamp,
+) -> ValidationResult<()> {
+    let exceeded = |limit, allowed| ValidationError::QuotaExceeded {
+        limit,
+        allowed,
+        resets_at: QuotaPolicy::resets_at(now),
+    };
+    if let Some(allowed) = policy.queries.filter(|&allowed| queries >= allowed) {
+        return Err(exceeded(QuotaLimit::Queries, allowed as u64));
+    }
+    if let Some(allowed) = policy
+        .bytes
+        .filter(|&allowed| bytes.saturating_add(size) > allowed)
+    {
+        return Err(exceeded(QuotaLimit::Bytes, allowed));
+    }
+    Ok(())
+}
+
+/// Validate a quota policy an owner sets on content or a node.
+///
+/// The policy must set at least one limit, and no limit may be zero.
+pub fn validate_quota_policy(policy: &QuotaPolicy) -> ValidationResult<()> {
+    if policy.is_unlimited() {
+        return Err(ValidationError::InvalidQuota {
+            reason: "set a query or byte limit".to_string(),
+        });
+    }
+    if policy.queries == Some(0) || policy.bytes == Some(0) {
+        return Err(ValidationError::InvalidQuota {
+            reason: "limits must be non-zero".to_string(),
+        });
+    }
+    Ok(())
+}
+
 /// Check if a peer is the owner of the content.
 ///
 /// Owners always have access to their own content.
@@ -123,7 +262,7 @@ pub fn validate_access_with_owner_bypass(
 mod tests {
     use super::*;
     use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
-    use nodalync_types::{AccessControl, Metadata};
+    use nodalync_types::{AccessControl, Metadata, QUOTA_PERIOD_MS};
 
     fn test_peer_id() -> PeerId {
         let (_, public_key) = generate_identity();
@@ -306,6 +445,20 @@ mod tests {
         assert!(validate_access_basic(&requester, &manifest).is_ok());
     }
 
+    #[test]
+    fn test_embargo() {
+        let mut manifest = create_test_manifest(Visibility::Shared);
+        assert!(validate_embargo(&manifest, 1000).is_ok());
+
+        manifest.access.publish_at = Some(2000);
+        let result = validate_embargo(&manifest, 1999);
+        assert!(matches!(
+            result,
+            Err(ValidationError::Embargoed { publish_at: 2000 })
+        ));
+        assert!(validate_embargo(&manifest, 2000).is_ok());
+    }
+
     #[test]
     fn test_is_owner() {
         let manifest = create_test_manifest(Visibility::Private);
@@ -341,4 +494,106 @@ mod tests {
         // Owner still has access (owner bypass)
         assert!(validate_access_with_owner_bypass(&owner, &manifest, None).is_ok());
     }
+
+    #[test]
+    fn test_unlisted_groups() {
+        use nodalync_types::Group;
+        use std::collections::HashMap;
+
+        let member = test_peer_id();
+        let listed = test_peer_id();
+        let (_, group_key) = generate_identity();
+        let group = Group::new(
+            peer_id_from_public_key(&group_key),
+            group_key,
+            "research",
+            vec![member],
+            1,
+            1_000,
+        );
+        let groups: HashMap<_, _> = [(group.id, group.clone())].into();
+
+        let mut manifest = create_test_manifest(Visibility::Unlisted);
+        manifest.access = AccessControl::with_groups(vec![group.id]);
+
+        // Members pass only when groups are resolved
+        assert!(validate_access_with_groups(&member, &manifest, None, Some(&groups)).is_ok());
+        assert!(matches!(
+            validate_access(&member, &manifest, None),
+            Err(ValidationError::NotInAllowlist)
+        ));
+        assert!(matches!(
+            validate_access_with_groups(&test_peer_id(), &manifest, None, Some(&groups)),
+            Err(ValidationError::NotInAllowlist)
+        ));
+
+        // Allowlist and groups combine; the denylist still applies
+        manifest.access.allowlist = Some(vec![listed]);
+        assert!(validate_access_with_groups(&listed, &manifest, None, Some(&groups)).is_ok());
+        assert!(validate_access_with_groups(&member, &manifest, None, Some(&groups)).is_ok());
+        manifest.access.denylist = Some(vec![member]);
+        assert!(matches!(
+            validate_access_with_groups(&member, &manifest, None, Some(&groups)),
+            Err(ValidationError::InDenylist)
+        ));
+    }
+
+    #[test]
+    fn test_trial() {
+        let requester = test_peer_id();
+        let mut manifest = create_test_manifest(Visibility::Shared);
+        assert!(matches!(
+            validate_trial(&manifest, 0),
+            Err(ValidationError::TrialExhausted { allowed: 0 })
+        ));
+
+        manifest.access.trial = Some(TrialPolicy::new(2));
+        assert!(validate_access(&requester, &manifest, None).is_ok());
+        assert!(validate_trial(&manifest, 0).is_ok());
+        assert!(validate_trial(&manifest, 1).is_ok());
+        assert!(matches!(
+            validate_trial(&manifest, 2),
+            Err(ValidationError::TrialExhausted { allowed: 2 })
+        ));
+
+        assert!(validate_trial_policy(&TrialPolicy::new(1).with_bytes(512)).is_ok());
+        assert!(validate_trial_policy(&TrialPolicy::new(0)).is_err());
+        assert!(validate_trial_policy(&TrialPolicy::new(MAX_TRIAL_READS + 1)).is_err());
+        assert!(validate_trial_policy(&TrialPolicy::new(1).with_bytes(0)).is_err());
+    }
+
+    #[test]
+    fn test_quota() {
+        let now = 3 * QUOTA_PERIOD_MS + 1_000;
+        let policy = QuotaPolicy::default().with_queries(2).with_bytes(100);
+        assert!(validate_quota(&policy, 1, 99, 1, now).is_ok());
+        assert_eq!(
+            validate_quota(&policy, 2, 0, 0, now),
+            Err(ValidationError::QuotaExceeded {
+                limit: QuotaLimit::Queries,
+                allowed: 2,
+                resets_at: 4 * QUOTA_PERIOD_MS,
+            })
+        );
+        // The query's own size counts, so a first query can't take more
+        // than the limit
+        for (used, size) in [(100, 1), (99, 2), (0, 1 << 30), (u64::MAX, u64::MAX)] {
+            assert!(matches!(
+                validate_quota(&policy, 0, used, size, now),
+                Err(ValidationError::QuotaExceeded {
+                    limit: QuotaLimit::Bytes,
+                    allowed: 100,
+                    ..
+                })
+            ));
+        }
+        // A byte-only quota doesn't limit queries
+        let bytes_only = QuotaPolicy::default().with_bytes(100);
+        assert!(validate_quota(&bytes_only, 1_000, 0, 100, now).is_ok());
+
+        assert!(validate_quota_policy(&policy).is_ok());
+        assert!(validate_quota_policy(&QuotaPolicy::default()).is_err());
+        assert!(validate_quota_policy(&QuotaPolicy::default().with_queries(0)).is_err());
+        assert!(validate_quota_policy(&bytes_only.with_queries(5)).is_ok());
+    }
 }
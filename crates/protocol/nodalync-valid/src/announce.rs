@@ -0,0 +1,192 @@
+//! Announcement validation.
+//!
+//! Announcements are signed by the publisher's identity key, which travels
+//! in the payload as `publisher_key`. The signature covers every other field,
+//! so a relaying node can't change the price, addresses or
+//! `publisher_peer_id` of an announcement without invalidating it.
+//!
+//! Unsigned announcements (from nodes that predate signing) are accepted but
+//! their publisher is unverified.
+//!
+//! A replica announcing its primary's content signs with its own key and
+//! attaches the primary's delegation naming it. The announcement then
+//! verifies as the primary's, provided the delegation is valid and names
+//! the signer.
+
+use nodalync_crypto::{peer_id_from_public_key, sign, verify, PeerId, PrivateKey, Timestamp};
+use nodalync_wire::{encode_payload, AnnouncePayload};
+
+use crate::error::{ValidationError, ValidationResult};
+use crate::replica::validate_replica_delegation;
+
+/// Construct the message bytes for announcement signing/verification.
+///
+/// The message is the CBOR encoding of the payload with `signature` unset.
+pub fn construct_announce_message(payload: &AnnouncePayload) -> ValidationResult<Vec<u8>> {
+    let unsigned = AnnouncePayload {
+        signature: None,
+        ..payload.clone()
+    };
+    encode_payload(&unsigned).map_err(|_| ValidationError::InvalidAnnouncementSignature)
+}
+
+/// Sign an announcement as its publisher.
+///
+/// Sets `publisher_key` to the key's public half and `signature` to the
+/// signature over the rest of the payload.
+pub fn sign_announcement(
+    private_key: &PrivateKey,
+    payload: &mut AnnouncePayload,
+) -> ValidationResult<()> {
+    payload.publisher_key = Some(private_key.public_key());
+    let message = construct_announce_message(payload)?;
+    payload.signature = Some(sign(private_key, &message));
+    Ok(())
+}
+
+/// Validate an announcement's publisher signature.
+///
+/// # Returns
+///
+/// - `Ok(Some(publisher))` if the announcement is signed by `publisher`, or
+///   by a replica holding `publisher`'s delegation valid at `now`
+/// - `Ok(None)` if the announcement is unsigned
+/// - `Err(InvalidAnnouncementSignature)` if only one of `publisher_key` and
+///   `signature` is set, the signature doesn't verify, or a delegation is
+///   attached to an unsigned announcement or doesn't name the signer
+/// - `Err(InvalidReplicaDelegation)` if the attached delegation is invalid
+pub fn validate_announcement(
+    payload: &AnnouncePayload,
+    now: Timestamp,
+) -> ValidationResult<Option<PeerId>> {
+    let (publisher_key, signature) = match (&payload.publisher_key, &payload.signature) {
+        (None, None) if payload.delegation.is_none() => return Ok(None),
+        (Some(key), Some(signature)) => (key, signature),
+        _ => return Err(ValidationError::InvalidAnnouncementSignature),
+    };
+
+    let message = construct_announce_message(payload)?;
+    if !verify(publisher_key, &message, signature) {
+        return Err(ValidationError::InvalidAnnouncementSignature);
+    }
+
+    let signer = peer_id_from_public_key(publisher_key);
+    match &payload.delegation {
+        None => Ok(Some(signer)),
+        Some(delegation) => {
+            if delegation.replica != signer {
+                return Err(ValidationError::InvalidAnnouncementSignature);
+            }
+            validate_replica_delegation(delegation, now)?;
+            Ok(Some(delegation.primary))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replica::sign_replica_delegation;
+    use nodalync_crypto::{content_hash, generate_identity};
+    use nodalync_types::{ContentType, L1Summary, ReplicaDelegation};
+
+    fn create_test_announcement() -> AnnouncePayload {
+        let hash = content_hash(b"announced");
+        AnnouncePayload {
+            hash,
+            content_type: ContentType::L0,
+            title: "Announced".to_string(),
+            l1_summary: L1Summary::empty(hash),
+            price: 100,
+            addresses: vec!["/ip4/127.0.0.1/tcp/9000".to_string()],
+            publisher_peer_id: Some("12D3KooWPublisher".to_string()),
+            publisher_key: None,
+            signature: None,
+            delegation: None,
+        }
+    }
+
+    #[test]
+    fn test_unsigned_announcement() {
+        let payload = create_test_announcement();
+        assert_eq!(validate_announcement(&payload, 0).unwrap(), None);
+    }
+
+    #[test]
+    fn test_signed_announcement() {
+        let (private_key, public_key) = generate_identity();
+        let mut payload = create_test_announcement();
+        sign_announcement(&private_key, &mut payload).unwrap();
+
+        assert_eq!(payload.publisher_key, Some(public_key));
+        assert_eq!(
+            validate_announcement(&payload, 0).unwrap(),
+            Some(peer_id_from_public_key(&public_key))
+        );
+    }
+
+    #[test]
+    fn test_tampered_announcement() {
+        let (private_key, _) = generate_identity();
+        let mut payload = create_test_announcement();
+        sign_announcement(&private_key, &mut payload).unwrap();
+
+        let mut spoofed = payload.clone();
+        spoofed.publisher_peer_id = Some("12D3KooWAttacker".to_string());
+        assert!(matches!(
+            validate_announcement(&spoofed, 0),
+            Err(ValidationError::InvalidAnnouncementSignature)
+        ));
+
+        // Swapping in another key doesn't verify either
+        let (_, other_key) = generate_identity();
+        let mut rekeyed = payload.clone();
+        rekeyed.publisher_key = Some(other_key);
+        assert!(validate_announcement(&rekeyed, 0).is_err());
+
+        // A key without a signature is rejected rather than treated as unsigned
+        let mut unsigned = payload;
+        unsigned.signature = None;
+        assert!(validate_announcement(&unsigned, 0).is_err());
+    }
+
+    #[test]
+    fn test_replica_announcement() {
+        let (primary_key, primary_public) = generate_identity();
+        let (replica_key, replica_public) = generate_identity();
+        let primary = peer_id_from_public_key(&primary_public);
+        let mut delegation = ReplicaDelegation::new(
+            primary,
+            primary_public,
+            peer_id_from_public_key(&replica_public),
+            None,
+            1_000,
+            2_000,
+        );
+        sign_replica_delegation(&primary_key, &mut delegation);
+
+        let mut payload = create_test_announcement();
+        payload.delegation = Some(delegation.clone());
+        sign_announcement(&replica_key, &mut payload).unwrap();
+        assert_eq!(
+            validate_announcement(&payload, 1_500).unwrap(),
+            Some(primary)
+        );
+
+        // Not once the delegation has expired
+        assert!(matches!(
+            validate_announcement(&payload, 2_000),
+            Err(ValidationError::InvalidReplicaDelegation { .. })
+        ));
+
+        // Nor signed by anyone but the named replica
+        let (other_key, _) = generate_identity();
+        sign_announcement(&other_key, &mut payload).unwrap();
+        assert!(validate_announcement(&payload, 1_500).is_err());
+
+        // A delegation doesn't make an unsigned announcement acceptable
+        let mut unsigned = create_test_announcement();
+        unsigned.delegation = Some(delegation);
+        assert!(validate_announcement(&unsigned, 1_500).is_err());
+    }
+}
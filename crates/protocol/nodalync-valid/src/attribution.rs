@@ -0,0 +1,242 @@
+//! Attribution certificate validation.
+//!
+//! A certificate is only worth attaching to a model output if anyone can
+//! check it offline. Validation recomputes the hash of the terms, checks
+//! the owner's DID belongs to the owner, and verifies every proof: each
+//! must be signed by the content owner or a root source owner, identified
+//! by a `did:key` DID URL, and the first must be the owner's own.
+
+use nodalync_crypto::{
+    did_from_public_key, peer_id_from_public_key, public_key_from_did, public_key_to_multibase,
+    sign, verify, PeerId, PrivateKey, Timestamp,
+};
+use nodalync_types::attribution::ATTRIBUTION_CERTIFICATE_TYPE;
+use nodalync_types::{AttributionCertificate, AttributionProof};
+
+use crate::error::{ValidationError, ValidationResult};
+
+/// Sign an attribution certificate.
+///
+/// Recomputes the hash from the terms, then adds a proof for the signer,
+/// replacing any earlier proof from the same key.
+pub fn sign_attribution_certificate(
+    private_key: &PrivateKey,
+    certificate: &mut AttributionCertificate,
+    created: Timestamp,
+) {
+    certificate.hash = certificate.compute_hash();
+
+    let public_key = private_key.public_key();
+    let verification_method = format!(
+        "{}#{}",
+        did_from_public_key(&public_key),
+        public_key_to_multibase(&public_key)
+    );
+    certificate
+        .proofs
+        .retain(|proof| proof.verification_method != verification_method);
+    certificate.proofs.push(AttributionProof {
+        verification_method,
+        created,
+        signature: sign(private_key, &certificate.hash.0),
+    });
+}
+
+/// Validate an attribution certificate and its proofs.
+///
+/// Checks:
+/// 1. The JSON-LD type is `AttributionCertificate`
+/// 2. `hash` matches the terms
+/// 3. `owner_did` is the DID of `owner`
+/// 4. The content is derived: `derived_from` and `sources` are non-empty,
+///    and every source has a positive weight
+/// 5. The first proof is the owner's, every proof is from the owner or a
+///    source owner, no signer appears twice, and every signature over
+///    `hash` verifies
+///
+/// # Returns
+/// The PeerIds of the signers, in proof order.
+pub fn validate_attribution_certificate(
+    certificate: &AttributionCertificate,
+) -> ValidationResult<Vec<PeerId>> {
+    if certificate.certificate_type != ATTRIBUTION_CERTIFICATE_TYPE {
+        return Err(invalid(format!(
+            "unexpected type {}",
+            certificate.certificate_type
+        )));
+    }
+
+    if certificate.hash != certificate.compute_hash() {
+        return Err(invalid("hash does not match the certificate terms"));
+    }
+
+    let owner_key = public_key_from_did(&certificate.owner_did)
+        .map_err(|e| invalid(format!("owner DID: {}", e)))?;
+    if peer_id_from_public_key(&owner_key) != certificate.owner {
+        return Err(invalid("owner DID does not belong to the owner"));
+    }
+
+    if certificate.derived_from.is_empty() || certificate.sources.is_empty() {
+        return Err(invalid("content is not derived"));
+    }
+    if certificate.sources.iter().any(|source| source.weight == 0) {
+        return Err(invalid("source with zero weight"));
+    }
+
+    if certificate.proofs.is_empty() {
+        return Err(invalid("certificate is not signed"));
+    }
+
+    let mut signers: Vec<PeerId> = Vec::with_capacity(certificate.proofs.len());
+    for proof in &certificate.proofs {
+        let key = public_key_from_did(&proof.verification_method)
+            .map_err(|e| invalid(format!("proof verification method: {}", e)))?;
+        let signer = peer_id_from_public_key(&key);
+
+        if signers.is_empty() && signer != certificate.owner {
+            return Err(invalid("first proof is not the owner's"));
+        }
+        if signer != certificate.owner && !certificate.sources.iter().any(|s| s.owner == signer) {
+            return Err(invalid(format!(
+                "proof from {}, who owns no source",
+                signer
+            )));
+        }
+        if signers.contains(&signer) {
+            return Err(invalid(format!("duplicate proof from {}", signer)));
+        }
+        if !verify(&key, &certificate.hash.0, &proof.signature) {
+            return Err(ValidationError::InvalidAttributionSignature);
+        }
+        signers.push(signer);
+    }
+
+    Ok(signers)
+}
+
+fn invalid(reason: impl Into<String>) -> ValidationError {
+    ValidationError::InvalidAttributionCertificate {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, generate_identity};
+    use nodalync_types::{Provenance, ProvenanceEntry, Visibility};
+
+    struct Fixture {
+        owner: PrivateKey,
+        source_owner: PrivateKey,
+        certificate: AttributionCertificate,
+    }
+
+    fn fixture() -> Fixture {
+        let (owner, owner_key) = generate_identity();
+        let (source_owner, source_key) = generate_identity();
+        let a = content_hash(b"source a");
+        let provenance = Provenance::new_derived(
+            vec![ProvenanceEntry::new(
+                a,
+                peer_id_from_public_key(&source_key),
+                Visibility::Shared,
+            )],
+            vec![a],
+            1,
+        );
+        let mut certificate = AttributionCertificate::new(
+            content_hash(b"insight"),
+            "Insight",
+            &owner_key,
+            &provenance,
+            vec![],
+            1_000,
+        );
+        sign_attribution_certificate(&owner, &mut certificate, 1_000);
+        Fixture {
+            owner,
+            source_owner,
+            certificate,
+        }
+    }
+
+    #[test]
+    fn test_valid_certificate() {
+        let Fixture {
+            owner,
+            source_owner,
+            mut certificate,
+        } = fixture();
+        let owner_id = peer_id_from_public_key(&owner.public_key());
+        assert_eq!(
+            validate_attribution_certificate(&certificate).unwrap(),
+            vec![owner_id]
+        );
+
+        // Source owners can endorse the certificate
+        sign_attribution_certificate(&source_owner, &mut certificate, 2_000);
+        let signers = validate_attribution_certificate(&certificate).unwrap();
+        assert_eq!(signers.len(), 2);
+        assert_eq!(signers[1], certificate.sources[0].owner);
+
+        // Signing again replaces the earlier proof
+        sign_attribution_certificate(&source_owner, &mut certificate, 3_000);
+        assert_eq!(certificate.proofs.len(), 2);
+        assert!(validate_attribution_certificate(&certificate).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_certificates() {
+        let Fixture {
+            source_owner,
+            certificate,
+            ..
+        } = fixture();
+
+        // Changed terms
+        let mut bad = certificate.clone();
+        bad.sources[0].weight = 5;
+        assert!(validate_attribution_certificate(&bad).is_err());
+
+        // Changed terms with a recomputed hash no longer match the signature
+        bad.hash = bad.compute_hash();
+        assert!(matches!(
+            validate_attribution_certificate(&bad),
+            Err(ValidationError::InvalidAttributionSignature)
+        ));
+
+        // Unsigned
+        let mut bad = certificate.clone();
+        bad.proofs.clear();
+        assert!(validate_attribution_certificate(&bad).is_err());
+
+        // Signed only by a source owner
+        let mut bad = certificate.clone();
+        bad.proofs.clear();
+        sign_attribution_certificate(&source_owner, &mut bad, 1_000);
+        assert!(validate_attribution_certificate(&bad).is_err());
+
+        // Endorsed by someone who owns no source
+        let (stranger, _) = generate_identity();
+        let mut bad = certificate.clone();
+        sign_attribution_certificate(&stranger, &mut bad, 1_000);
+        assert!(matches!(
+            validate_attribution_certificate(&bad),
+            Err(ValidationError::InvalidAttributionCertificate { .. })
+        ));
+
+        // Owner DID of another key
+        let (_, other_key) = generate_identity();
+        let mut bad = certificate.clone();
+        bad.owner_did = did_from_public_key(&other_key);
+        bad.hash = bad.compute_hash();
+        assert!(validate_attribution_certificate(&bad).is_err());
+
+        // Not derived
+        let mut bad = certificate;
+        bad.derived_from.clear();
+        bad.hash = bad.compute_hash();
+        assert!(validate_attribution_certificate(&bad).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+//! Publisher bond validation.
+//!
+//! Publishers post a bond on-chain as a stake against serving bad content,
+//! and record it in the `economics.bond` of their manifests. A node can
+//! require a minimum bond for each visibility level before it accepts a
+//! publisher's content, which makes flooding the network from many cheap
+//! identities costly. The manifest's claim must meet the requirement and,
+//! when a [`BondChecker`] is available, the chain must back it.
+
+use nodalync_types::{Amount, Manifest, Visibility};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ValidationError, ValidationResult};
+use crate::payment::BondChecker;
+
+/// Minimum publisher bonds, by visibility.
+///
+/// Private and offline content is never served, so it needs no bond.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BondRequirements {
+    /// Bond required to serve unlisted content
+    pub unlisted: Amount,
+    /// Bond required to serve shared content
+    pub shared: Amount,
+}
+
+impl BondRequirements {
+    /// Create requirements that require no bond.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the bond required for unlisted content.
+    pub fn with_unlisted(mut self, amount: Amount) -> Self {
+        self.unlisted = amount;
+        self
+    }
+
+    /// Set the bond required for shared content.
+    pub fn with_shared(mut self, amount: Amount) -> Self {
+        self.shared = amount;
+        self
+    }
+
+    /// The bond required for content with `visibility`.
+    pub fn required_for(&self, visibility: Visibility) -> Amount {
+        match visibility {
+            Visibility::Unlisted => self.unlisted,
+            Visibility::Shared => self.shared,
+            _ => 0,
+        }
+    }
+
+    /// Whether no bond is required at any visibility.
+    pub fn is_empty(&self) -> bool {
+        self.unlisted == 0 && self.shared == 0
+    }
+}
+
+/// Validate a manifest's publisher bond against the requirements.
+///
+/// Checks:
+/// 1. The manifest claims at least the bond its visibility requires
+/// 2. If a bond checker is given, the owner has that bond posted
+///
+/// Without a bond checker only the claim is checked.
+pub fn validate_publisher_bond(
+    manifest: &Manifest,
+    requirements: &BondRequirements,
+    bond_checker: Option<&dyn BondChecker>,
+) -> ValidationResult<()> {
+    let required = requirements.required_for(manifest.visibility);
+    if required == 0 {
+        return Ok(());
+    }
+
+    // 1. Claimed in the manifest
+    let claimed = manifest.economics.bond.as_ref().map_or(0, |b| b.amount);
+    if claimed < required {
+        return Err(ValidationError::PublisherBondRequired { required, claimed });
+    }
+
+    // 2. Backed on-chain
+    if let Some(checker) = bond_checker {
+        if !checker.has_bond(&manifest.owner, required) {
+            return Err(ValidationError::PublisherBondUnbacked { required });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_types::{Metadata, PeerId, PublisherBond};
+
+    struct PostedBond(Amount);
+
+    impl BondChecker for PostedBond {
+        fn has_bond(&self, _peer_id: &PeerId, amount: u64) -> bool {
+            self.0 >= amount
+        }
+    }
+
+    fn manifest(visibility: Visibility, bond: Option<Amount>) -> Manifest {
+        let (_, public_key) = generate_identity();
+        let hash = content_hash(b"content");
+        let mut manifest = Manifest::new_l0(
+            hash,
+            peer_id_from_public_key(&public_key),
+            Metadata::new("Test", 7),
+            1000,
+        );
+        manifest.visibility = visibility;
+        manifest.economics.bond = bond.map(|amount| PublisherBond::new(amount, "0.0.1@1.1"));
+        manifest
+    }
+
+    #[test]
+    fn test_requirements_by_visibility() {
+        let requirements = BondRequirements::new().with_unlisted(100).with_shared(500);
+
+        assert_eq!(requirements.required_for(Visibility::Shared), 500);
+        assert_eq!(requirements.required_for(Visibility::Unlisted), 100);
+        assert_eq!(requirements.required_for(Visibility::Private), 0);
+        assert!(!requirements.is_empty());
+        assert!(BondRequirements::new().is_empty());
+
+        // Unlisted content only needs the smaller bond
+        let unlisted = manifest(Visibility::Unlisted, Some(100));
+        assert!(validate_publisher_bond(&unlisted, &requirements, None).is_ok());
+        let shared = manifest(Visibility::Shared, Some(100));
+        assert!(matches!(
+            validate_publisher_bond(&shared, &requirements, None),
+            Err(ValidationError::PublisherBondRequired {
+                required: 500,
+                claimed: 100
+            })
+        ));
+    }
+
+    #[test]
+    fn test_claim_must_be_backed() {
+        let requirements = BondRequirements::new().with_shared(500);
+
+        let unbonded = manifest(Visibility::Shared, None);
+        assert!(matches!(
+            validate_publisher_bond(&unbonded, &requirements, Some(&PostedBond(1000))),
+            Err(ValidationError::PublisherBondRequired { claimed: 0, .. })
+        ));
+
+        let claimed = manifest(Visibility::Shared, Some(500));
+        assert!(validate_publisher_bond(&claimed, &requirements, Some(&PostedBond(500))).is_ok());
+
+        // A claim the chain doesn't back, e.g. after slashing
+        assert!(matches!(
+            validate_publisher_bond(&claimed, &requirements, Some(&PostedBond(200))),
+            Err(ValidationError::PublisherBondUnbacked { required: 500 })
+        ));
+    }
+}
@@ -0,0 +1,106 @@
+//! Canonical version pointer validation.
+//!
+//! Under the owner-canonical fork policy, a pointer decides which of a
+//! lineage's forked versions peers treat as current, so it must only be
+//! honoured from the lineage's owner. Validation checks the hash, that the
+//! key belongs to the owner, the signature, and that the owner is the
+//! lineage's known owner when the caller knows it.
+
+use nodalync_crypto::{peer_id_from_public_key, sign, verify, PrivateKey};
+use nodalync_types::{CanonicalVersion, PeerId};
+
+use crate::error::{ValidationError, ValidationResult};
+
+/// Sign a canonical version pointer as the lineage owner.
+///
+/// Recomputes the hash from the terms before signing it.
+pub fn sign_canonical_version(private_key: &PrivateKey, pointer: &mut CanonicalVersion) {
+    pointer.hash = pointer.compute_hash();
+    pointer.signature = sign(private_key, &pointer.hash.0);
+}
+
+/// Validate a canonical version pointer and its owner signature.
+///
+/// Checks:
+/// 1. `hash` matches the terms
+/// 2. `owner` is derived from `owner_key`
+/// 3. The signature over `hash` verifies against `owner_key`
+/// 4. `owner` is `lineage_owner`, if the lineage's owner is known
+pub fn validate_canonical_version(
+    pointer: &CanonicalVersion,
+    lineage_owner: Option<&PeerId>,
+) -> ValidationResult<()> {
+    if pointer.hash != pointer.compute_hash() {
+        return Err(invalid("hash does not match the pointer terms"));
+    }
+
+    if pointer.owner != peer_id_from_public_key(&pointer.owner_key) {
+        return Err(invalid("owner does not match owner key"));
+    }
+
+    if !verify(&pointer.owner_key, &pointer.hash.0, &pointer.signature) {
+        return Err(invalid("signature does not verify"));
+    }
+
+    if lineage_owner.is_some_and(|owner| *owner != pointer.owner) {
+        return Err(invalid("not issued by the lineage owner"));
+    }
+
+    Ok(())
+}
+
+fn invalid(reason: impl Into<String>) -> ValidationError {
+    ValidationError::InvalidCanonicalVersion {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, generate_identity};
+
+    fn signed_pointer() -> (CanonicalVersion, PrivateKey) {
+        let (private_key, public_key) = generate_identity();
+        let mut pointer = CanonicalVersion::new(
+            content_hash(b"v1"),
+            content_hash(b"v3"),
+            peer_id_from_public_key(&public_key),
+            public_key,
+            1_000,
+        );
+        sign_canonical_version(&private_key, &mut pointer);
+        (pointer, private_key)
+    }
+
+    #[test]
+    fn test_valid_pointer() {
+        let (pointer, _) = signed_pointer();
+        assert!(validate_canonical_version(&pointer, None).is_ok());
+        assert!(validate_canonical_version(&pointer, Some(&pointer.owner)).is_ok());
+    }
+
+    #[test]
+    fn test_tampered_pointer_rejected() {
+        let (pointer, _) = signed_pointer();
+
+        let mut tampered = pointer.clone();
+        tampered.version = content_hash(b"other v3");
+        assert!(validate_canonical_version(&tampered, None).is_err());
+
+        // Re-hashed but not re-signed
+        tampered.hash = tampered.compute_hash();
+        assert!(validate_canonical_version(&tampered, None).is_err());
+    }
+
+    #[test]
+    fn test_pointer_from_another_owner_rejected() {
+        let (pointer, _) = signed_pointer();
+        let (_, other_key) = generate_identity();
+        let other = peer_id_from_public_key(&other_key);
+        assert!(matches!(
+            validate_canonical_version(&pointer, Some(&other)),
+            Err(ValidationError::InvalidCanonicalVersion { .. })
+        ));
+    }
+}
@@ -0,0 +1,188 @@
+//! Capability token validation.
+//!
+//! A capability token grants access to one piece of content whatever its
+//! visibility, so it is only honoured if the content's owner issued it.
+//! Validation checks the token is for the requested content, that the
+//! issuer owns it, the hash, key and signature, the expiry, and that the
+//! requester is the grantee when the token is bound to one.
+
+use nodalync_crypto::{peer_id_from_public_key, sign, verify, PrivateKey};
+use nodalync_types::{CapabilityToken, Manifest, PeerId, Timestamp};
+
+use crate::error::{ValidationError, ValidationResult};
+
+/// Sign a capability token as its issuer.
+///
+/// Recomputes the hash from the terms before signing it.
+pub fn sign_capability(private_key: &PrivateKey, token: &mut CapabilityToken) {
+    token.hash = token.compute_hash();
+    token.signature = sign(private_key, &token.hash.0);
+}
+
+/// Validate a capability token presented by `requester` for `manifest`.
+///
+/// Checks:
+/// 1. `content_hash == manifest.hash`
+/// 2. `issuer == manifest.owner`
+/// 3. `expires_at > issued_at`
+/// 4. `hash` matches the terms
+/// 5. `issuer` is derived from `issuer_key`
+/// 6. The signature over `hash` verifies against `issuer_key`
+/// 7. The token has not expired at `now`
+/// 8. `grantee`, if set, is the requester
+pub fn validate_capability(
+    token: &CapabilityToken,
+    requester: &PeerId,
+    manifest: &Manifest,
+    now: Timestamp,
+) -> ValidationResult<()> {
+    if token.content_hash != manifest.hash {
+        return Err(invalid("token is for different content"));
+    }
+
+    if token.issuer != manifest.owner {
+        return Err(invalid("token was not issued by the content owner"));
+    }
+
+    if token.expires_at <= token.issued_at {
+        return Err(invalid("expires before it was issued"));
+    }
+
+    if token.hash != token.compute_hash() {
+        return Err(invalid("hash does not match the token terms"));
+    }
+
+    if token.issuer != peer_id_from_public_key(&token.issuer_key) {
+        return Err(invalid("issuer does not match issuer key"));
+    }
+
+    if !verify(&token.issuer_key, &token.hash.0, &token.signature) {
+        return Err(ValidationError::InvalidCapabilitySignature);
+    }
+
+    if token.is_expired(now) {
+        return Err(ValidationError::CapabilityExpired {
+            expires_at: token.expires_at,
+        });
+    }
+
+    if !token.allows(requester) {
+        return Err(invalid("token is bound to another peer"));
+    }
+
+    Ok(())
+}
+
+fn invalid(reason: impl Into<String>) -> ValidationError {
+    ValidationError::InvalidCapability {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, generate_identity};
+    use nodalync_types::{Metadata, Visibility};
+
+    fn create_manifest(owner: PeerId) -> Manifest {
+        let content = b"Private research notes";
+        let hash = content_hash(content);
+        let mut manifest = Manifest::new_l0(
+            hash,
+            owner,
+            Metadata::new("Notes", content.len() as u64),
+            1_000,
+        );
+        manifest.visibility = Visibility::Private;
+        manifest
+    }
+
+    fn create_signed_token(grantee: Option<PeerId>) -> (CapabilityToken, Manifest, PrivateKey) {
+        let (private_key, public_key) = generate_identity();
+        let owner = peer_id_from_public_key(&public_key);
+        let manifest = create_manifest(owner);
+        let mut token =
+            CapabilityToken::new(manifest.hash, owner, public_key, grantee, 1_000, 2_000);
+        sign_capability(&private_key, &mut token);
+        (token, manifest, private_key)
+    }
+
+    #[test]
+    fn test_valid_capability() {
+        let grantee = PeerId([7u8; 20]);
+        let (token, manifest, _) = create_signed_token(Some(grantee));
+        assert!(validate_capability(&token, &grantee, &manifest, 1_500).is_ok());
+
+        let (bearer, manifest, _) = create_signed_token(None);
+        assert!(validate_capability(&bearer, &PeerId([8u8; 20]), &manifest, 1_500).is_ok());
+    }
+
+    #[test]
+    fn test_capability_binding() {
+        let grantee = PeerId([7u8; 20]);
+        let (token, manifest, _) = create_signed_token(Some(grantee));
+
+        // Bound to another peer
+        assert!(matches!(
+            validate_capability(&token, &PeerId([8u8; 20]), &manifest, 1_500),
+            Err(ValidationError::InvalidCapability { .. })
+        ));
+
+        // Other content from the same owner
+        let mut other = manifest.clone();
+        other.hash = content_hash(b"other");
+        assert!(matches!(
+            validate_capability(&token, &grantee, &other, 1_500),
+            Err(ValidationError::InvalidCapability { .. })
+        ));
+
+        // Content owned by someone else
+        let mut foreign = manifest.clone();
+        foreign.owner = PeerId([9u8; 20]);
+        assert!(matches!(
+            validate_capability(&token, &grantee, &foreign, 1_500),
+            Err(ValidationError::InvalidCapability { .. })
+        ));
+    }
+
+    #[test]
+    fn test_capability_expiry() {
+        let (token, manifest, _) = create_signed_token(None);
+        assert!(matches!(
+            validate_capability(&token, &PeerId([7u8; 20]), &manifest, 2_000),
+            Err(ValidationError::CapabilityExpired { expires_at: 2_000 })
+        ));
+    }
+
+    #[test]
+    fn test_capability_tampering() {
+        let grantee = PeerId([7u8; 20]);
+        let (token, manifest, private_key) = create_signed_token(Some(grantee));
+
+        // Extending the expiry without re-signing changes the hash
+        let mut extended = token.clone();
+        extended.expires_at = 10_000;
+        assert!(validate_capability(&extended, &grantee, &manifest, 1_500).is_err());
+
+        // Re-hashed but not re-signed
+        extended.hash = extended.compute_hash();
+        assert!(matches!(
+            validate_capability(&extended, &grantee, &manifest, 1_500),
+            Err(ValidationError::InvalidCapabilitySignature)
+        ));
+
+        // Signed with another key claiming the owner's identity
+        let (other_key, other_public) = generate_identity();
+        let mut forged = token.clone();
+        forged.issuer_key = other_public;
+        sign_capability(&other_key, &mut forged);
+        assert!(validate_capability(&forged, &grantee, &manifest, 1_500).is_err());
+
+        // The owner re-signing is fine
+        let mut renewed = token;
+        renewed.expires_at = 10_000;
+        sign_capability(&private_key, &mut renewed);
+        assert!(validate_capability(&renewed, &grantee, &manifest, 5_000).is_ok());
+    }
+}
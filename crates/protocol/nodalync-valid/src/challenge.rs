@@ -0,0 +1,174 @@
+//! Query challenge validation.
+//!
+//! Before serving expensive content a provider can challenge the requester
+//! with a single-use nonce. The requester signs the nonce, bound to the
+//! content hash, the provider and itself, with the identity key of the
+//! peer the request (and payment channel) belongs to. A third party that
+//! captured an earlier request can't answer a fresh challenge, so replaying
+//! the request gets it nothing.
+
+use nodalync_crypto::{content_hash, peer_id_from_public_key, sign, verify, Hash, PrivateKey};
+use nodalync_types::{PeerId, Timestamp};
+use nodalync_wire::{ChallengeResponse, QueryChallenge};
+
+use crate::error::{ValidationError, ValidationResult};
+
+/// Domain separation tag for challenge signatures.
+const CHALLENGE_DOMAIN: &[u8] = b"nodalync:query-challenge";
+
+/// Construct the message a requester signs to answer a challenge.
+///
+/// `H("nodalync:query-challenge" || nonce || content_hash || provider || requester)`
+pub fn construct_challenge_message(
+    nonce: &Hash,
+    content: &Hash,
+    provider: &PeerId,
+    requester: &PeerId,
+) -> Hash {
+    content_hash(
+        &[
+            CHALLENGE_DOMAIN,
+            nonce.0.as_slice(),
+            content.0.as_slice(),
+            provider.0.as_slice(),
+            requester.0.as_slice(),
+        ]
+        .concat(),
+    )
+}
+
+/// Answer a challenge for a query of `content`, as the holder of
+/// `private_key`.
+pub fn sign_challenge(
+    private_key: &PrivateKey,
+    challenge: &QueryChallenge,
+    content: &Hash,
+) -> ChallengeResponse {
+    let public_key = private_key.public_key();
+    let requester = peer_id_from_public_key(&public_key);
+    let message =
+        construct_challenge_message(&challenge.nonce, content, &challenge.provider, &requester);
+    ChallengeResponse {
+        nonce: challenge.nonce,
+        public_key,
+        signature: sign(private_key, &message.0),
+    }
+}
+
+/// Validate a requester's answer to the challenge issued to it.
+///
+/// Checks:
+/// 1. The response answers `challenge` (same nonce)
+/// 2. The challenge has not expired at `now`
+/// 3. `public_key` belongs to `requester`
+/// 4. The signature over the challenge message verifies
+pub fn validate_challenge_response(
+    response: &ChallengeResponse,
+    challenge: &QueryChallenge,
+    content: &Hash,
+    requester: &PeerId,
+    now: Timestamp,
+) -> ValidationResult<()> {
+    if response.nonce != challenge.nonce {
+        return Err(invalid("nonce does not match the challenge"));
+    }
+
+    if now > challenge.expires_at {
+        return Err(invalid("challenge expired"));
+    }
+
+    if peer_id_from_public_key(&response.public_key) != *requester {
+        return Err(invalid("public key does not belong to the requester"));
+    }
+
+    let message =
+        construct_challenge_message(&challenge.nonce, content, &challenge.provider, requester);
+    if !verify(&response.public_key, &message.0, &response.signature) {
+        return Err(ValidationError::InvalidChallengeSignature);
+    }
+
+    Ok(())
+}
+
+fn invalid(reason: impl Into<String>) -> ValidationError {
+    ValidationError::InvalidChallengeResponse {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::generate_identity;
+
+    fn challenge() -> QueryChallenge {
+        QueryChallenge {
+            nonce: content_hash(b"nonce"),
+            provider: PeerId([7u8; 20]),
+            expires_at: 2_000,
+        }
+    }
+
+    #[test]
+    fn test_valid_challenge_response() {
+        let (private_key, public_key) = generate_identity();
+        let requester = peer_id_from_public_key(&public_key);
+        let content = content_hash(b"content");
+        let challenge = challenge();
+
+        let response = sign_challenge(&private_key, &challenge, &content);
+        assert!(
+            validate_challenge_response(&response, &challenge, &content, &requester, 1_000).is_ok()
+        );
+    }
+
+    #[test]
+    fn test_challenge_response_rejected() {
+        let (private_key, public_key) = generate_identity();
+        let requester = peer_id_from_public_key(&public_key);
+        let content = content_hash(b"content");
+        let challenge = challenge();
+        let response = sign_challenge(&private_key, &challenge, &content);
+
+        // Expired
+        assert!(matches!(
+            validate_challenge_response(&response, &challenge, &content, &requester, 3_000),
+            Err(ValidationError::InvalidChallengeResponse { .. })
+        ));
+
+        // Answering a different challenge
+        let fresh = QueryChallenge {
+            nonce: content_hash(b"fresh"),
+            ..challenge.clone()
+        };
+        assert!(matches!(
+            validate_challenge_response(&response, &fresh, &content, &requester, 1_000),
+            Err(ValidationError::InvalidChallengeResponse { .. })
+        ));
+
+        // Signed for other content
+        let other = content_hash(b"other");
+        assert_eq!(
+            validate_challenge_response(&response, &challenge, &other, &requester, 1_000),
+            Err(ValidationError::InvalidChallengeSignature)
+        );
+
+        // Signed by someone other than the requester
+        let (other_key, _) = generate_identity();
+        let forged = sign_challenge(&other_key, &challenge, &content);
+        assert!(matches!(
+            validate_challenge_response(&forged, &challenge, &content, &requester, 1_000),
+            Err(ValidationError::InvalidChallengeResponse { .. })
+        ));
+
+        // A borrowed key with someone else's signature
+        let spliced = ChallengeResponse {
+            public_key,
+            ..forged
+        };
+        assert_eq!(
+            validate_challenge_response(&spliced, &challenge, &content, &requester, 1_000),
+            Err(ValidationError::InvalidChallengeSignature)
+        );
+    }
+}
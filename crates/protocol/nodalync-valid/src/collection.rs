@@ -0,0 +1,245 @@
+//! Collection validation.
+//!
+//! This module validates collections (curated lists of content):
+//! - Item count, weight and title limits
+//! - Items are unique L0/L1/L3 content (no L2, no nested collections)
+//! - Provenance is derived from the items, weighted per item
+//! - Manifest price equals the bundle price
+
+use std::collections::{HashMap, HashSet};
+
+use nodalync_types::{
+    Collection, ContentType, Hash, Manifest, MAX_COLLECTION_ITEMS, MAX_COLLECTION_ITEM_WEIGHT,
+    MAX_TITLE_LENGTH,
+};
+
+use crate::error::{ValidationError, ValidationResult};
+use crate::provenance::{compute_weighted_root_entries, roots_match, validate_provenance};
+
+/// Validate a collection against its manifest and the item manifests.
+///
+/// Checks all collection validation rules:
+/// 1. Content type must be Collection
+/// 2. At least one and at most `MAX_COLLECTION_ITEMS` items
+/// 3. Item weights in `1..=MAX_COLLECTION_ITEM_WEIGHT`, titles within limit
+/// 4. No duplicate items and no self-reference
+/// 5. `derived_from` lists the items in order
+/// 6. Items are L0, L1 or L3 content
+/// 7. Provenance roots equal the weighted roots of the items
+/// 8. Manifest price equals the bundle price (0 if none)
+///
+/// # Arguments
+///
+/// * `collection` - The collection to validate
+/// * `manifest` - The manifest describing the collection
+/// * `items` - Manifests of the collection's items
+///
+/// # Returns
+///
+/// `Ok(())` if all validations pass, or `Err(ValidationError)`.
+pub fn validate_collection(
+    collection: &Collection,
+    manifest: &Manifest,
+    items: &[Manifest],
+) -> ValidationResult<()> {
+    // 1. Content type must be Collection
+    if manifest.content_type != ContentType::Collection {
+        return Err(ValidationError::Internal(
+            "manifest content_type must be Collection".to_string(),
+        ));
+    }
+
+    // 2. Item count within limits
+    if collection.items.is_empty() {
+        return Err(ValidationError::CollectionEmpty);
+    }
+    if collection.items.len() > MAX_COLLECTION_ITEMS {
+        return Err(ValidationError::CollectionTooManyItems {
+            count: collection.items.len(),
+            max: MAX_COLLECTION_ITEMS,
+        });
+    }
+
+    // 3 & 4. Per-item checks
+    let mut seen = HashSet::new();
+    for item in &collection.items {
+        if item.weight == 0 || item.weight > MAX_COLLECTION_ITEM_WEIGHT {
+            return Err(ValidationError::CollectionInvalidWeight {
+                hash: format!("{}", item.hash),
+                weight: item.weight,
+                max: MAX_COLLECTION_ITEM_WEIGHT,
+            });
+        }
+        if item.title.len() > MAX_TITLE_LENGTH {
+            return Err(ValidationError::TitleTooLong {
+                length: item.title.len(),
+                max: MAX_TITLE_LENGTH,
+            });
+        }
+        if item.hash == manifest.hash {
+            return Err(ValidationError::SelfReference);
+        }
+        if !seen.insert(item.hash) {
+            return Err(ValidationError::CollectionDuplicateItem {
+                hash: format!("{}", item.hash),
+            });
+        }
+    }
+
+    // 5. derived_from lists the items in order
+    if manifest.provenance.derived_from != collection.hashes() {
+        return Err(ValidationError::CollectionItemsMismatch);
+    }
+
+    // 6. Items must be known L0/L1/L3 content
+    let by_hash: HashMap<&Hash, &Manifest> = items.iter().map(|m| (&m.hash, m)).collect();
+    let mut weighted = Vec::with_capacity(collection.items.len());
+    for item in &collection.items {
+        let member = by_hash
+            .get(&item.hash)
+            .ok_or_else(|| ValidationError::UnknownSource {
+                hash: format!("{}", item.hash),
+            })?;
+        match member.content_type {
+            ContentType::L0 | ContentType::L1 | ContentType::L3 => {}
+            other => {
+                return Err(ValidationError::CollectionInvalidItemType {
+                    hash: format!("{}", item.hash),
+                    content_type: format!("{:?}", other),
+                });
+            }
+        }
+        weighted.push((*member, item.weight));
+    }
+
+    // 7. Provenance derived from the items with weighted roots
+    validate_provenance(manifest, items)?;
+    let computed_roots = compute_weighted_root_entries(&weighted);
+    if !roots_match(&manifest.provenance.root_l0l1, &computed_roots) {
+        return Err(ValidationError::RootEntriesMismatch);
+    }
+
+    // 8. Price equals the bundle price
+    if manifest.economics.price != collection.price() {
+        return Err(ValidationError::CollectionPriceMismatch {
+            expected: collection.price(),
+            actual: manifest.economics.price,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+    use nodalync_types::{CollectionItem, Metadata, PeerId, Provenance};
+
+    fn test_peer_id() -> PeerId {
+        let (_, public_key) = generate_identity();
+        peer_id_from_public_key(&public_key)
+    }
+
+    fn create_l0_manifest(content: &[u8]) -> Manifest {
+        let hash = content_hash(content);
+        let metadata = Metadata::new("Item", content.len() as u64);
+        Manifest::new_l0(hash, test_peer_id(), metadata, 1234567890)
+    }
+
+    fn create_collection_manifest(collection: &Collection, items: &[Manifest]) -> Manifest {
+        let content = serde_json::to_vec(collection).unwrap();
+        let sources: Vec<_> = collection
+            .items
+            .iter()
+            .map(|item| {
+                let m = items.iter().find(|m| m.hash == item.hash).unwrap();
+                (m.hash, &m.provenance, m.owner, m.visibility, item.weight)
+            })
+            .collect();
+
+        let mut manifest = create_l0_manifest(&content);
+        manifest.content_type = ContentType::Collection;
+        manifest.provenance = Provenance::from_weighted_sources(&sources);
+        manifest.economics.price = collection.price();
+        manifest
+    }
+
+    fn sample() -> (Collection, Vec<Manifest>) {
+        let a = create_l0_manifest(b"item a");
+        let b = create_l0_manifest(b"item b");
+        let collection = Collection::new("Reading list")
+            .with_item(CollectionItem::new(a.hash, "A"))
+            .with_item(CollectionItem::new(b.hash, "B").with_weight(3))
+            .with_bundle_price(400);
+        (collection, vec![a, b])
+    }
+
+    #[test]
+    fn test_valid_collection() {
+        let (collection, items) = sample();
+        let manifest = create_collection_manifest(&collection, &items);
+        assert!(validate_collection(&collection, &manifest, &items).is_ok());
+    }
+
+    #[test]
+    fn test_collection_structure_errors() {
+        let (collection, items) = sample();
+        let manifest = create_collection_manifest(&collection, &items);
+
+        let empty = Collection::new("Empty");
+        assert!(matches!(
+            validate_collection(&empty, &manifest, &items),
+            Err(ValidationError::CollectionEmpty)
+        ));
+
+        let mut zero = collection.clone();
+        zero.items[0].weight = 0;
+        assert!(matches!(
+            validate_collection(&zero, &manifest, &items),
+            Err(ValidationError::CollectionInvalidWeight { .. })
+        ));
+
+        let mut duplicate = collection.clone();
+        duplicate.items.push(duplicate.items[0].clone());
+        assert!(matches!(
+            validate_collection(&duplicate, &manifest, &items),
+            Err(ValidationError::CollectionDuplicateItem { .. })
+        ));
+    }
+
+    #[test]
+    fn test_collection_weight_and_price_mismatch() {
+        let (collection, items) = sample();
+        let manifest = create_collection_manifest(&collection, &items);
+
+        // Changing a weight without rebuilding provenance breaks the roots
+        let mut reweighted = collection.clone();
+        reweighted.items[1].weight = 1;
+        assert!(matches!(
+            validate_collection(&reweighted, &manifest, &items),
+            Err(ValidationError::RootEntriesMismatch)
+        ));
+
+        let repriced = collection.clone().with_bundle_price(1);
+        assert!(matches!(
+            validate_collection(&repriced, &manifest, &items),
+            Err(ValidationError::CollectionPriceMismatch {
+                expected: 1,
+                actual: 400
+            })
+        ));
+    }
+
+    #[test]
+    fn test_collection_rejects_l2_items() {
+        let (collection, mut items) = sample();
+        let manifest = create_collection_manifest(&collection, &items);
+        items[0].content_type = ContentType::L2;
+
+        assert!(matches!(
+            validate_collection(&collection, &manifest, &items),
+            Err(ValidationError::CollectionInvalidItemType { .. })
+        ));
+    }
+}
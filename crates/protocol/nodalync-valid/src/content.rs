@@ -3,12 +3,18 @@
 //! This module validates content against its manifest, including:
 //! - Hash verification
 //! - Size verification
-//! - Metadata constraints (title, description, tags)
+//! - Metadata constraints (title, description, tags, structured fields,
+//!   preview policy, chunk tree, priced sections, metered units)
+//! - Slices served for a single section
 
 use nodalync_crypto::content_hash;
 use nodalync_types::{
-    Manifest, MAX_CONTENT_SIZE, MAX_DESCRIPTION_LENGTH, MAX_TAGS, MAX_TAG_LENGTH, MAX_TITLE_LENGTH,
+    ChunkTree, ContentSection, Manifest, PreviewPolicy, MAX_CHUNK_SIZE, MAX_CONTENT_SIZE,
+    MAX_DESCRIPTION_LENGTH, MAX_METADATA_FIELDS_SIZE, MAX_METERED_UNITS, MAX_PRICE,
+    MAX_REDACTION_RULES, MAX_REDACTION_RULE_LENGTH, MAX_SCHEMA_URI_LENGTH, MAX_SECTIONS, MAX_TAGS,
+    MAX_TAG_LENGTH, MAX_TITLE_LENGTH, MIN_METERED_UNIT_SIZE,
 };
+use regex::Regex;
 
 use crate::error::{ValidationError, ValidationResult};
 
@@ -21,6 +27,9 @@ use crate::error::{ValidationError, ValidationResult};
 /// 4. Description length <= MAX_DESCRIPTION_LENGTH (2000)
 /// 5. Tags count <= MAX_TAGS (20), each tag <= MAX_TAG_LENGTH (50)
 /// 6. Content size <= MAX_CONTENT_SIZE
+/// 7. Each recorded chunk hash matches its chunk of the content
+/// 8. Each priced section's hash matches its slice of the content
+/// 9. Each metered unit's hash matches its unit of the content
 ///
 /// # Arguments
 ///
@@ -77,6 +86,59 @@ pub fn validate_content(content: &[u8], manifest: &Manifest) -> ValidationResult
     // Validate metadata constraints
     validate_metadata(manifest)?;
 
+    // 4. Chunk hashes match the content
+    if let Some(ref tree) = manifest.metadata.chunks {
+        for (index, chunk) in content.chunks(tree.chunk_size as usize).enumerate() {
+            if !tree.verify_chunk(index as u32, chunk) {
+                return Err(ValidationError::InvalidChunkTree {
+                    reason: format!("chunk {} does not match its hash", index),
+                });
+            }
+        }
+    }
+
+    // 5. Section hashes match the content
+    for (index, section) in manifest.metadata.sections.iter().enumerate() {
+        if !section.verify(&content[section.range()]) {
+            return Err(ValidationError::InvalidSection {
+                reason: format!("section {} does not match its hash", index),
+            });
+        }
+    }
+
+    // 6. Metered unit hashes match the content
+    if let Some(ref tree) = manifest.metadata.metering {
+        for (index, unit) in content.chunks(tree.chunk_size as usize).enumerate() {
+            if !tree.verify_chunk(index as u32, unit) {
+                return Err(ValidationError::InvalidMetering {
+                    reason: format!("unit {} does not match its hash", index),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate a slice served for one section of the content.
+///
+/// The section must exist in the manifest, and the slice must hash to the
+/// section's recorded hash at its offset.
+pub fn validate_section_slice(
+    manifest: &Manifest,
+    index: u32,
+    slice: &[u8],
+) -> ValidationResult<()> {
+    let Some(section) = manifest.metadata.section(index) else {
+        return Err(ValidationError::InvalidSection {
+            reason: format!("no section {}", index),
+        });
+    };
+    if !section.verify(slice) {
+        return Err(ValidationError::InvalidSection {
+            reason: format!("slice does not match section {}", index),
+        });
+    }
     Ok(())
 }
 
@@ -87,6 +149,17 @@ pub fn validate_content(content: &[u8], manifest: &Manifest) -> ValidationResult
 /// - Description length <= MAX_DESCRIPTION_LENGTH (if present)
 /// - Tags count <= MAX_TAGS
 /// - Each tag length <= MAX_TAG_LENGTH
+/// - Schema URI length <= MAX_SCHEMA_URI_LENGTH (if present)
+/// - Structured fields size <= MAX_METADATA_FIELDS_SIZE, and only with a schema
+/// - Preview policy rules within limits, and patterns that compile
+/// - Chunk size within MAX_CHUNK_SIZE, with one hash per chunk of the content
+/// - At most MAX_SECTIONS sections, titled, in order, without overlaps, and
+///   within the content
+/// - Metered units of at least MIN_METERED_UNIT_SIZE, between two and
+///   MAX_METERED_UNITS of them, each costing at least one tinybar
+///
+/// Conformance of the fields to their schema needs the schema itself; see
+/// [`validate_structured_metadata`](crate::validate_structured_metadata).
 pub fn validate_metadata(manifest: &Manifest) -> ValidationResult<()> {
     // Title length
     if manifest.metadata.title.len() > MAX_TITLE_LENGTH {
@@ -125,6 +198,194 @@ pub fn validate_metadata(manifest: &Manifest) -> ValidationResult<()> {
         }
     }
 
+    // Schema URI length (if present)
+    if let Some(ref uri) = manifest.metadata.schema {
+        if uri.len() > MAX_SCHEMA_URI_LENGTH {
+            return Err(ValidationError::SchemaUriTooLong {
+                length: uri.len(),
+                max: MAX_SCHEMA_URI_LENGTH,
+            });
+        }
+    }
+
+    // Structured fields size, and never without a schema
+    if let Some(ref fields) = manifest.metadata.fields {
+        if fields.len() > MAX_METADATA_FIELDS_SIZE {
+            return Err(ValidationError::FieldsTooLarge {
+                size: fields.len(),
+                max: MAX_METADATA_FIELDS_SIZE,
+            });
+        }
+        if manifest.metadata.schema.is_none() {
+            return Err(ValidationError::FieldsWithoutSchema);
+        }
+    }
+
+    if let Some(ref policy) = manifest.metadata.preview_policy {
+        validate_preview_policy(policy)?;
+    }
+
+    if let Some(ref tree) = manifest.metadata.chunks {
+        validate_chunk_tree(tree, manifest.metadata.content_size)?;
+    }
+
+    validate_sections(&manifest.metadata.sections, manifest.metadata.content_size)?;
+
+    if let Some(ref tree) = manifest.metadata.metering {
+        validate_metering(tree, manifest)?;
+    }
+
+    Ok(())
+}
+
+/// Validate the shape of the priced sections against the content size.
+fn validate_sections(sections: &[ContentSection], content_size: u64) -> ValidationResult<()> {
+    let invalid = |reason: String| ValidationError::InvalidSection { reason };
+
+    if sections.len() > MAX_SECTIONS {
+        return Err(invalid(format!(
+            "{} sections (max {})",
+            sections.len(),
+            MAX_SECTIONS
+        )));
+    }
+
+    let mut previous_end = 0;
+    for (index, section) in sections.iter().enumerate() {
+        if section.title.trim().is_empty() || section.title.len() > MAX_TITLE_LENGTH {
+            return Err(invalid(format!(
+                "section {} title must be 1..={} characters",
+                index, MAX_TITLE_LENGTH
+            )));
+        }
+        if section.length == 0 {
+            return Err(invalid(format!("section {} is empty", index)));
+        }
+        if section.offset < previous_end {
+            return Err(invalid(format!(
+                "section {} overlaps or precedes the section before it",
+                index
+            )));
+        }
+        if section.end() > content_size {
+            return Err(invalid(format!(
+                "section {} ends at {} past the content size {}",
+                index,
+                section.end(),
+                content_size
+            )));
+        }
+        if section.price > MAX_PRICE {
+            return Err(invalid(format!(
+                "section {} price {} exceeds {}",
+                index, section.price, MAX_PRICE
+            )));
+        }
+        previous_end = section.end();
+    }
+
+    Ok(())
+}
+
+/// Validate the metered units against the content size and price.
+///
+/// Paying per unit only makes sense for paid content in more than one unit,
+/// and the price must cover a tinybar per unit so every unit costs something.
+fn validate_metering(tree: &ChunkTree, manifest: &Manifest) -> ValidationResult<()> {
+    let invalid = |reason: String| ValidationError::InvalidMetering { reason };
+
+    if tree.chunk_size < MIN_METERED_UNIT_SIZE || tree.chunk_size > MAX_CHUNK_SIZE {
+        return Err(invalid(format!(
+            "unit size {} outside {}..={}",
+            tree.chunk_size, MIN_METERED_UNIT_SIZE, MAX_CHUNK_SIZE
+        )));
+    }
+
+    let units = ChunkTree::chunk_count(manifest.metadata.content_size, tree.chunk_size);
+    if tree.len() as u64 != units {
+        return Err(invalid(format!(
+            "{} unit hashes for {} units",
+            tree.len(),
+            units
+        )));
+    }
+    if units < 2 {
+        return Err(invalid("content fits in a single unit".to_string()));
+    }
+    if tree.len() > MAX_METERED_UNITS {
+        return Err(invalid(format!(
+            "{} units (max {})",
+            tree.len(),
+            MAX_METERED_UNITS
+        )));
+    }
+    if manifest.economics.price < units {
+        return Err(invalid(format!(
+            "price {} doesn't cover {} units",
+            manifest.economics.price, units
+        )));
+    }
+
+    Ok(())
+}
+
+/// Validate the shape of a chunk tree against the content size.
+fn validate_chunk_tree(tree: &ChunkTree, content_size: u64) -> ValidationResult<()> {
+    if tree.chunk_size == 0 || tree.chunk_size > MAX_CHUNK_SIZE {
+        return Err(ValidationError::InvalidChunkTree {
+            reason: format!(
+                "chunk size {} outside 1..={}",
+                tree.chunk_size, MAX_CHUNK_SIZE
+            ),
+        });
+    }
+
+    let expected = ChunkTree::chunk_count(content_size, tree.chunk_size);
+    if tree.len() as u64 != expected {
+        return Err(ValidationError::InvalidChunkTree {
+            reason: format!("{} chunk hashes for {} chunks", tree.len(), expected),
+        });
+    }
+
+    Ok(())
+}
+
+/// Validate preview redaction rules.
+///
+/// Each list holds at most MAX_REDACTION_RULES non-empty rules of at most
+/// MAX_REDACTION_RULE_LENGTH characters, and every pattern must compile.
+fn validate_preview_policy(policy: &PreviewPolicy) -> ValidationResult<()> {
+    let invalid = |reason: String| ValidationError::InvalidPreviewPolicy { reason };
+
+    for (kind, rules) in [
+        ("patterns", &policy.redact_patterns),
+        ("sections", &policy.redact_sections),
+    ] {
+        if rules.len() > MAX_REDACTION_RULES {
+            return Err(invalid(format!(
+                "{} redaction {} exceeds maximum {}",
+                rules.len(),
+                kind,
+                MAX_REDACTION_RULES
+            )));
+        }
+        for rule in rules {
+            if rule.trim().is_empty() {
+                return Err(invalid(format!("empty redaction {}", kind)));
+            }
+            if rule.chars().count() > MAX_REDACTION_RULE_LENGTH {
+                return Err(invalid(format!(
+                    "redaction rule exceeds {} characters",
+                    MAX_REDACTION_RULE_LENGTH
+                )));
+            }
+        }
+    }
+
+    for pattern in &policy.redact_patterns {
+        Regex::new(pattern).map_err(|e| invalid(format!("pattern {:?}: {}", pattern, e)))?;
+    }
+
     Ok(())
 }
 
@@ -282,4 +543,197 @@ mod tests {
         let hash2 = content_hash(content2);
         assert_ne!(hash1, hash2);
     }
+
+    #[test]
+    fn test_structured_field_limits() {
+        let content = b"Test content";
+        let mut manifest = create_test_manifest(content, "Dataset");
+        manifest.metadata = manifest
+            .metadata
+            .with_fields("nodalync:schema/dataset/v1", r#"{"format":"csv"}"#);
+        assert!(validate_content(content, &manifest).is_ok());
+
+        // Fields without a schema
+        let mut no_schema = manifest.clone();
+        no_schema.metadata.schema = None;
+        assert!(matches!(
+            validate_metadata(&no_schema),
+            Err(ValidationError::FieldsWithoutSchema)
+        ));
+
+        // Oversized schema URI
+        let mut long_uri = manifest.clone();
+        long_uri.metadata.schema = Some("x".repeat(MAX_SCHEMA_URI_LENGTH + 1));
+        assert!(matches!(
+            validate_metadata(&long_uri),
+            Err(ValidationError::SchemaUriTooLong { .. })
+        ));
+
+        // Oversized fields
+        let mut large = manifest;
+        large.metadata.fields = Some(format!(
+            r#"{{"notes":"{}"}}"#,
+            "x".repeat(MAX_METADATA_FIELDS_SIZE)
+        ));
+        assert!(matches!(
+            validate_metadata(&large),
+            Err(ValidationError::FieldsTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_preview_policy_limits() {
+        let content = b"Test content";
+        let mut manifest = create_test_manifest(content, "Report");
+        manifest.metadata.preview_policy = Some(
+            PreviewPolicy::new()
+                .with_pattern(r"\b\d{3}-\d{2}-\d{4}\b")
+                .with_section("Appendix"),
+        );
+        assert!(validate_content(content, &manifest).is_ok());
+
+        let check = |policy: PreviewPolicy| {
+            let mut manifest = manifest.clone();
+            manifest.metadata.preview_policy = Some(policy);
+            validate_metadata(&manifest)
+        };
+
+        // Patterns must compile
+        assert!(matches!(
+            check(PreviewPolicy::new().with_pattern("(unclosed")),
+            Err(ValidationError::InvalidPreviewPolicy { .. })
+        ));
+
+        // Empty and oversized rules
+        assert!(check(PreviewPolicy::new().with_section("  ")).is_err());
+        assert!(check(
+            PreviewPolicy::new().with_pattern("x".repeat(MAX_REDACTION_RULE_LENGTH + 1))
+        )
+        .is_err());
+
+        // Too many rules
+        let mut many = PreviewPolicy::new();
+        for i in 0..=MAX_REDACTION_RULES {
+            many = many.with_section(format!("Section {}", i));
+        }
+        assert!(check(many).is_err());
+    }
+
+    #[test]
+    fn test_chunk_tree() {
+        let content = b"Content in three chunks";
+        let mut manifest = create_test_manifest(content, "Chunked");
+        manifest.metadata.chunks = Some(ChunkTree::build(content, 8));
+        assert!(validate_content(content, &manifest).is_ok());
+
+        // A tampered chunk hash
+        let mut tampered = manifest.clone();
+        tampered.metadata.chunks.as_mut().unwrap().hashes[1] = content_hash(b"other");
+        assert!(matches!(
+            validate_content(content, &tampered),
+            Err(ValidationError::InvalidChunkTree { .. })
+        ));
+
+        // Too few hashes for the content size
+        let mut short = manifest.clone();
+        short.metadata.chunks.as_mut().unwrap().hashes.pop();
+        assert!(validate_metadata(&short).is_err());
+
+        // Chunk sizes out of range
+        let mut zero = manifest.clone();
+        zero.metadata.chunks = Some(ChunkTree {
+            chunk_size: 0,
+            hashes: Vec::new(),
+        });
+        assert!(validate_metadata(&zero).is_err());
+        let mut huge = manifest;
+        huge.metadata.chunks = Some(ChunkTree::build(content, MAX_CHUNK_SIZE + 1));
+        assert!(validate_metadata(&huge).is_err());
+    }
+
+    #[test]
+    fn test_sections() {
+        let content = b"# Summary\nshort\n# Details\nlong";
+        let mut manifest = create_test_manifest(content, "Sectioned");
+        manifest.metadata.sections = vec![
+            ContentSection::new("Summary", content, 0..16, 10),
+            ContentSection::new("Details", content, 16..content.len(), 40),
+        ];
+        assert!(validate_content(content, &manifest).is_ok());
+
+        // A served slice must match its section
+        assert!(validate_section_slice(&manifest, 1, &content[16..]).is_ok());
+        assert!(validate_section_slice(&manifest, 0, &content[16..]).is_err());
+        assert!(validate_section_slice(&manifest, 2, b"").is_err());
+
+        // A tampered section hash
+        let mut tampered = manifest.clone();
+        tampered.metadata.sections[0].hash = content_hash(b"other");
+        assert!(matches!(
+            validate_content(content, &tampered),
+            Err(ValidationError::InvalidSection { .. })
+        ));
+
+        let check = |sections: Vec<ContentSection>| {
+            let mut manifest = manifest.clone();
+            manifest.metadata.sections = sections;
+            validate_metadata(&manifest)
+        };
+        let section = |title: &str, range: std::ops::Range<usize>| {
+            ContentSection::new(title, content, range, 10)
+        };
+
+        // Out of order or overlapping
+        assert!(check(vec![section("B", 16..20), section("A", 0..16)]).is_err());
+        assert!(check(vec![section("A", 0..17), section("B", 16..20)]).is_err());
+        // Untitled, empty, or past the end of the content
+        assert!(check(vec![section(" ", 0..16)]).is_err());
+        assert!(check(vec![section("A", 4..4)]).is_err());
+        let mut past_end = section("A", 0..16);
+        past_end.length = content.len() as u64 + 1;
+        assert!(check(vec![past_end]).is_err());
+        // Too many
+        let many = (0..=MAX_SECTIONS).map(|_| section("A", 0..0)).collect();
+        assert!(matches!(
+            check(many),
+            Err(ValidationError::InvalidSection { .. })
+        ));
+    }
+
+    #[test]
+    fn test_metering() {
+        let content = vec![3u8; 2_500];
+        let mut manifest = create_test_manifest(&content, "Metered");
+        manifest.economics.price = 100;
+        manifest.metadata.metering = Some(ChunkTree::build(&content, 1_024));
+        assert!(validate_content(&content, &manifest).is_ok());
+
+        // A tampered unit hash
+        let mut tampered = manifest.clone();
+        tampered.metadata.metering.as_mut().unwrap().hashes[2] = content_hash(b"other");
+        assert!(matches!(
+            validate_content(&content, &tampered),
+            Err(ValidationError::InvalidMetering { .. })
+        ));
+
+        let check = |tree: ChunkTree, price: u64| {
+            let mut manifest = manifest.clone();
+            manifest.metadata.metering = Some(tree);
+            manifest.economics.price = price;
+            validate_metadata(&manifest)
+        };
+        // Units too small, a single unit, or too few hashes
+        assert!(check(ChunkTree::build(&content, 512), 100).is_err());
+        assert!(check(ChunkTree::build(&content, 4_096), 100).is_err());
+        let mut short = ChunkTree::build(&content, 1_024);
+        short.hashes.pop();
+        assert!(check(short, 100).is_err());
+        // Free, or too cheap for every unit to cost something
+        assert!(check(ChunkTree::build(&content, 1_024), 0).is_err());
+        assert!(matches!(
+            check(ChunkTree::build(&content, 1_024), 2),
+            Err(ValidationError::InvalidMetering { .. })
+        ));
+        assert!(check(ChunkTree::build(&content, 1_024), 3).is_ok());
+    }
 }
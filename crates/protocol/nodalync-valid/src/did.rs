@@ -0,0 +1,187 @@
+//! DID document and peer info validation.
+//!
+//! A `did:key` identifier encodes its public key, so a DID document can be
+//! verified without a registry: every verification method must carry the
+//! key of the DID, and the PeerId derived from that key identifies the
+//! peer. Peer info advertising a DID must advertise the DID of its own
+//! key, so a peer can't claim someone else's identity.
+
+use nodalync_crypto::{
+    did_from_public_key, peer_id_from_public_key, public_key_from_did, public_key_from_multibase,
+    PublicKey,
+};
+use nodalync_types::did::ED25519_VERIFICATION_KEY_2020;
+use nodalync_types::DidDocument;
+use nodalync_wire::PeerInfoPayload;
+
+use crate::error::{ValidationError, ValidationResult};
+
+/// Validate a DID document and return the public key it describes.
+///
+/// Checks:
+/// 1. `id` is a valid Ed25519 `did:key` identifier
+/// 2. There is at least one verification method
+/// 3. Each method is an `Ed25519VerificationKey2020` controlled by `id`,
+///    with an id under `id`, carrying the key of `id`
+/// 4. `authentication` and `assertion_method` only reference those methods
+pub fn validate_did_document(doc: &DidDocument) -> ValidationResult<PublicKey> {
+    let public_key = public_key_from_did(&doc.id).map_err(|e| invalid(e.to_string()))?;
+
+    if doc.verification_method.is_empty() {
+        return Err(invalid("no verification methods"));
+    }
+
+    for method in &doc.verification_method {
+        if !method
+            .id
+            .strip_prefix(doc.id.as_str())
+            .is_some_and(|fragment| fragment.starts_with('#'))
+        {
+            return Err(invalid(format!(
+                "verification method {} is not a DID URL of the document",
+                method.id
+            )));
+        }
+        if method.method_type != ED25519_VERIFICATION_KEY_2020 {
+            return Err(invalid(format!(
+                "unsupported verification method type {}",
+                method.method_type
+            )));
+        }
+        if method.controller != doc.id {
+            return Err(invalid(format!(
+                "verification method {} has another controller",
+                method.id
+            )));
+        }
+        let method_key = public_key_from_multibase(&method.public_key_multibase)
+            .map_err(|e| invalid(e.to_string()))?;
+        if method_key != public_key {
+            return Err(invalid(format!(
+                "verification method {} does not carry the key of the DID",
+                method.id
+            )));
+        }
+    }
+
+    for reference in doc.authentication.iter().chain(&doc.assertion_method) {
+        if !doc.verification_method.iter().any(|m| &m.id == reference) {
+            return Err(invalid(format!(
+                "unknown verification method {}",
+                reference
+            )));
+        }
+    }
+
+    Ok(public_key)
+}
+
+/// Validate peer info received from a peer.
+///
+/// Checks:
+/// 1. `peer_id` is derived from `public_key`
+/// 2. `did`, if present, is the `did:key` identifier of `public_key`
+pub fn validate_peer_info(info: &PeerInfoPayload) -> ValidationResult<()> {
+    if info.peer_id != peer_id_from_public_key(&info.public_key) {
+        return Err(invalid("peer id does not match public key"));
+    }
+
+    if let Some(did) = &info.did {
+        if *did != did_from_public_key(&info.public_key) {
+            return Err(invalid("DID does not match public key"));
+        }
+    }
+
+    Ok(())
+}
+
+fn invalid(reason: impl Into<String>) -> ValidationError {
+    ValidationError::InvalidDid {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{generate_identity, public_key_to_multibase};
+
+    #[test]
+    fn test_valid_did_document() {
+        let (_, public_key) = generate_identity();
+        let doc = DidDocument::new(&public_key);
+        assert_eq!(validate_did_document(&doc).unwrap(), public_key);
+    }
+
+    #[test]
+    fn test_invalid_did_documents() {
+        let (_, public_key) = generate_identity();
+        let (_, other_key) = generate_identity();
+        let doc = DidDocument::new(&public_key);
+        let other = DidDocument::new(&other_key);
+
+        // The id must be a did:key identifier
+        let mut bad = doc.clone();
+        bad.id = "did:web:example.com".to_string();
+        assert!(validate_did_document(&bad).is_err());
+
+        // A method carrying another key
+        let mut bad = doc.clone();
+        bad.verification_method[0].public_key_multibase = public_key_to_multibase(&other_key);
+        assert!(validate_did_document(&bad).is_err());
+
+        // A method controlled by another DID
+        let mut bad = doc.clone();
+        bad.verification_method[0].controller = other.id.clone();
+        assert!(validate_did_document(&bad).is_err());
+
+        // A method from another document
+        let mut bad = doc.clone();
+        bad.verification_method = other.verification_method.clone();
+        assert!(validate_did_document(&bad).is_err());
+
+        // Another key type
+        let mut bad = doc.clone();
+        bad.verification_method[0].method_type = "JsonWebKey2020".to_string();
+        assert!(validate_did_document(&bad).is_err());
+
+        // No methods
+        let mut bad = doc.clone();
+        bad.verification_method.clear();
+        assert!(validate_did_document(&bad).is_err());
+
+        // Dangling references
+        let mut bad = doc;
+        bad.authentication = other.authentication;
+        assert!(matches!(
+            validate_did_document(&bad),
+            Err(ValidationError::InvalidDid { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_peer_info() {
+        let (_, public_key) = generate_identity();
+        let (_, other_key) = generate_identity();
+        let mut info = PeerInfoPayload {
+            peer_id: peer_id_from_public_key(&public_key),
+            public_key,
+            addresses: vec![],
+            capabilities: vec![],
+            content_count: 0,
+            uptime: 0,
+            did: None,
+        };
+        assert!(validate_peer_info(&info).is_ok());
+
+        info.did = Some(did_from_public_key(&public_key));
+        assert!(validate_peer_info(&info).is_ok());
+
+        info.did = Some(did_from_public_key(&other_key));
+        assert!(validate_peer_info(&info).is_err());
+
+        info.did = None;
+        info.peer_id = peer_id_from_public_key(&other_key);
+        assert!(validate_peer_info(&info).is_err());
+    }
+}
@@ -0,0 +1,249 @@
+//! Re-download entitlement validation.
+//!
+//! A requester whose cached copy of bought content was evicted can present
+//! the provider's receipt from the paid query to be served the same
+//! content version again for free. The receipt's payment ID is derived
+//! from the content hash, the payer, the receipt timestamp and the channel
+//! nonce, so it can't be presented for other content or by another peer,
+//! and the provider's signature covers the payment ID, amount, timestamp
+//! and nonce. Providers still check the payment against their own record
+//! of it.
+
+use nodalync_crypto::{content_hash, verify, Hash, PeerId, PublicKey};
+use nodalync_types::Timestamp;
+use nodalync_wire::PaymentReceipt;
+
+use crate::error::{ValidationError, ValidationResult};
+use crate::payment::construct_receipt_message;
+
+/// The payment ID a provider gives its receipt for a query of `content`
+/// by `payer` at `timestamp`, paid at `channel_nonce`.
+///
+/// `H(content_hash || payer || timestamp (u64 BE) || channel_nonce (u64 BE))`
+pub fn receipt_payment_id(
+    content: &Hash,
+    payer: &PeerId,
+    timestamp: Timestamp,
+    channel_nonce: u64,
+) -> Hash {
+    content_hash(
+        &[
+            content.0.as_slice(),
+            payer.0.as_slice(),
+            &timestamp.to_be_bytes(),
+            &channel_nonce.to_be_bytes(),
+        ]
+        .concat(),
+    )
+}
+
+/// Validate a receipt presented by `requester` to re-download `content`.
+///
+/// Checks:
+/// 1. The receipt is for a payment (a free query entitles to nothing)
+/// 2. Its payment ID is that of a query of `content` by `requester` at its
+///    timestamp and channel nonce
+/// 3. It was not issued after `now`
+/// 4. `now` is within `window_ms` of the receipt timestamp
+///    (`EntitlementExpired`)
+/// 5. The signature verifies against `provider_key`
+///    (`InvalidEntitlementSignature`)
+pub fn validate_entitlement(
+    receipt: &PaymentReceipt,
+    content: &Hash,
+    requester: &PeerId,
+    provider_key: &PublicKey,
+    now: Timestamp,
+    window_ms: u64,
+) -> ValidationResult<()> {
+    if receipt.amount == 0 {
+        return Err(invalid("receipt is not for a payment"));
+    }
+
+    let payment_id =
+        receipt_payment_id(content, requester, receipt.timestamp, receipt.channel_nonce);
+    if receipt.payment_id != payment_id {
+        return Err(invalid("receipt is for other content or another payer"));
+    }
+
+    if receipt.timestamp > now {
+        return Err(invalid("receipt is from the future"));
+    }
+
+    let expires_at = receipt.timestamp.saturating_add(window_ms);
+    if now > expires_at {
+        return Err(ValidationError::EntitlementExpired { expires_at });
+    }
+
+    let message = construct_receipt_message(
+        &receipt.payment_id,
+        receipt.amount,
+        receipt.timestamp,
+        receipt.channel_nonce,
+    );
+    if !verify(provider_key, &message, &receipt.distributor_signature) {
+        return Err(ValidationError::InvalidEntitlementSignature);
+    }
+
+    Ok(())
+}
+
+fn invalid(reason: impl Into<String>) -> ValidationError {
+    ValidationError::InvalidEntitlement {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{
+        generate_identity, peer_id_from_public_key, sign, PrivateKey, Signature,
+    };
+
+    const WINDOW: u64 = 1_000_000;
+
+    fn peer() -> PeerId {
+        peer_id_from_public_key(&generate_identity().1)
+    }
+
+    fn receipt(
+        key: &PrivateKey,
+        content: &Hash,
+        payer: &PeerId,
+        amount: u64,
+        timestamp: u64,
+    ) -> PaymentReceipt {
+        let payment_id = receipt_payment_id(content, payer, timestamp, 4);
+        let message = construct_receipt_message(&payment_id, amount, timestamp, 4);
+        PaymentReceipt {
+            payment_id,
+            amount,
+            timestamp,
+            channel_nonce: 4,
+            distributor_signature: sign(key, &message),
+            app_fee: 0,
+            app_fee_recipient: None,
+            referral_fee: 0,
+            referrer: None,
+        }
+    }
+
+    #[test]
+    fn test_valid_entitlement() {
+        let (private_key, public_key) = generate_identity();
+        let content = content_hash(b"content");
+        let payer = peer();
+        let receipt = receipt(&private_key, &content, &payer, 100, 5_000);
+
+        assert!(
+            validate_entitlement(&receipt, &content, &payer, &public_key, 5_000, WINDOW).is_ok()
+        );
+        assert!(validate_entitlement(
+            &receipt,
+            &content,
+            &payer,
+            &public_key,
+            5_000 + WINDOW,
+            WINDOW
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_payers_at_same_timestamp() {
+        let (private_key, public_key) = generate_identity();
+        let content = content_hash(b"content");
+        let (alice, bob) = (peer(), peer());
+        let alice_receipt = receipt(&private_key, &content, &alice, 100, 5_000);
+        let bob_receipt = receipt(&private_key, &content, &bob, 100, 5_000);
+
+        // Two payers in the same millisecond get distinct entitlements
+        assert_ne!(alice_receipt.payment_id, bob_receipt.payment_id);
+        assert!(
+            validate_entitlement(&alice_receipt, &content, &alice, &public_key, 6_000, WINDOW)
+                .is_ok()
+        );
+        assert!(
+            validate_entitlement(&bob_receipt, &content, &bob, &public_key, 6_000, WINDOW).is_ok()
+        );
+
+        // As do two payments by one payer at different channel nonces
+        assert_ne!(
+            receipt_payment_id(&content, &alice, 5_000, 4),
+            receipt_payment_id(&content, &alice, 5_000, 5)
+        );
+    }
+
+    #[test]
+    fn test_expired_entitlement() {
+        let (private_key, public_key) = generate_identity();
+        let content = content_hash(b"content");
+        let payer = peer();
+        let receipt = receipt(&private_key, &content, &payer, 100, 5_000);
+
+        assert!(matches!(
+            validate_entitlement(&receipt, &content, &payer, &public_key, 5_001 + WINDOW, WINDOW),
+            Err(ValidationError::EntitlementExpired { expires_at }) if expires_at == 5_000 + WINDOW
+        ));
+    }
+
+    #[test]
+    fn test_invalid_entitlements() {
+        let (private_key, public_key) = generate_identity();
+        let content = content_hash(b"content");
+        let payer = peer();
+
+        // A free receipt entitles to nothing
+        let free = receipt(&private_key, &content, &payer, 0, 5_000);
+        assert!(matches!(
+            validate_entitlement(&free, &content, &payer, &public_key, 6_000, WINDOW),
+            Err(ValidationError::InvalidEntitlement { .. })
+        ));
+
+        // Receipts only cover the content they were issued for
+        let other = content_hash(b"other content");
+        let paid = receipt(&private_key, &content, &payer, 100, 5_000);
+        assert!(matches!(
+            validate_entitlement(&paid, &other, &payer, &public_key, 6_000, WINDOW),
+            Err(ValidationError::InvalidEntitlement { .. })
+        ));
+
+        // And the peer they were issued to
+        assert!(matches!(
+            validate_entitlement(&paid, &content, &peer(), &public_key, 6_000, WINDOW),
+            Err(ValidationError::InvalidEntitlement { .. })
+        ));
+
+        // Not before they were issued
+        assert!(matches!(
+            validate_entitlement(&paid, &content, &payer, &public_key, 4_000, WINDOW),
+            Err(ValidationError::InvalidEntitlement { .. })
+        ));
+
+        // Raising the amount breaks the signature
+        let raised = PaymentReceipt {
+            amount: 1_000,
+            ..paid.clone()
+        };
+        assert_eq!(
+            validate_entitlement(&raised, &content, &payer, &public_key, 6_000, WINDOW),
+            Err(ValidationError::InvalidEntitlementSignature)
+        );
+
+        // As does a receipt signed by another provider, or not at all
+        let (_, other_key) = generate_identity();
+        assert_eq!(
+            validate_entitlement(&paid, &content, &payer, &other_key, 6_000, WINDOW),
+            Err(ValidationError::InvalidEntitlementSignature)
+        );
+        let unsigned = PaymentReceipt {
+            distributor_signature: Signature::from_bytes([0u8; 64]),
+            ..paid
+        };
+        assert_eq!(
+            validate_entitlement(&unsigned, &content, &payer, &public_key, 6_000, WINDOW),
+            Err(ValidationError::InvalidEntitlementSignature)
+        );
+    }
+}
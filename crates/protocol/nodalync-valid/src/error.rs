@@ -4,6 +4,7 @@
 //! functions in this crate. Each variant corresponds to a specific type
 //! of validation failure as defined in Protocol Specification §9.
 
+use nodalync_types::QuotaLimit;
 use thiserror::Error;
 
 /// Errors that can occur during validation.
@@ -82,6 +83,84 @@ pub enum ValidationError {
         max: u64,
     },
 
+    // =========================================================================
+    // Structured Metadata Validation Errors
+    // =========================================================================
+    /// Schema URI exceeds maximum length
+    #[error("schema URI too long: {length} chars exceeds maximum {max}")]
+    SchemaUriTooLong {
+        /// Actual URI length
+        length: usize,
+        /// Maximum allowed length
+        max: usize,
+    },
+
+    /// Structured fields exceed maximum size
+    #[error("metadata fields too large: {size} bytes exceeds maximum {max}")]
+    FieldsTooLarge {
+        /// Actual size in bytes
+        size: usize,
+        /// Maximum allowed size
+        max: usize,
+    },
+
+    /// Structured fields given without a schema
+    #[error("metadata fields require a schema URI")]
+    FieldsWithoutSchema,
+
+    /// Schema URI doesn't resolve to a known schema
+    #[error("unknown metadata schema: {uri}")]
+    UnknownSchema {
+        /// The unresolved URI
+        uri: String,
+    },
+
+    /// Schema is malformed or uses unsupported keywords
+    #[error("invalid metadata schema: {reason}")]
+    InvalidSchema {
+        /// Reason the schema was rejected
+        reason: String,
+    },
+
+    /// Structured fields don't conform to their schema
+    #[error("invalid metadata field {path}: {reason}")]
+    InvalidFields {
+        /// JSON pointer to the offending value
+        path: String,
+        /// Reason for invalidity
+        reason: String,
+    },
+
+    /// Preview redaction rules are malformed or exceed limits
+    #[error("invalid preview policy: {reason}")]
+    InvalidPreviewPolicy {
+        /// Reason the policy was rejected
+        reason: String,
+    },
+
+    /// Chunk hashes don't describe the content
+    #[error("invalid chunk tree: {reason}")]
+    InvalidChunkTree {
+        /// Reason the chunk tree was rejected
+        reason: String,
+    },
+
+    /// Priced sections don't describe the content, or a served slice
+    /// doesn't match its section
+    #[error("invalid section: {reason}")]
+    InvalidSection {
+        /// Reason the section was rejected
+        reason: String,
+    },
+
+    /// Metered units don't describe the content, or don't each cost
+    /// something
+    #[error("invalid metering: {reason}")]
+    InvalidMetering {
+        /// Reason the metering was rejected
+        reason: String,
+    },
+
     // =========================================================================
     // Version Validation Errors (§9.2)
     // =========================================================================
@@ -188,6 +267,13 @@ pub enum ValidationError {
         max: u32,
     },
 
+    /// Claimed on-chain attestation is missing or doesn't match the manifest
+    #[error("attestation mismatch: {reason}")]
+    AttestationMismatch {
+        /// What didn't match
+        reason: String,
+    },
+
     // =========================================================================
     // Payment Validation Errors (§9.4)
     // =========================================================================
@@ -238,6 +324,15 @@ pub enum ValidationError {
         channel_nonce: u64,
     },
 
+    /// Nonce already used on the channel (replay)
+    #[error("replayed nonce: {nonce} must be greater than last recorded nonce {last_nonce}")]
+    ReplayedNonce {
+        /// Payment nonce
+        nonce: u64,
+        /// Highest nonce already recorded for the channel
+        last_nonce: u64,
+    },
+
     /// Payment signature is invalid
     #[error("invalid payment signature")]
     InvalidPaymentSignature,
@@ -246,6 +341,22 @@ pub enum ValidationError {
     #[error("payment provenance does not match manifest provenance")]
     ProvenanceMismatch,
 
+    /// Query names a referrer the content's referral policy can't pay
+    #[error("invalid referral: {reason}")]
+    InvalidReferral {
+        /// Reason the referral is refused
+        reason: String,
+    },
+
+    /// Referral cut is outside the allowed range
+    #[error("invalid referral cut: {basis_points} basis points (must be 1..={max})")]
+    InvalidReferralPolicy {
+        /// Offered cut in basis points
+        basis_points: u32,
+        /// Largest cut allowed
+        max: u32,
+    },
+
     // =========================================================================
     // Message Validation Errors (§9.5)
     // =========================================================================
@@ -289,6 +400,151 @@ pub enum ValidationError {
         reason: String,
     },
 
+    /// Announcement publisher signature is missing a part or invalid
+    #[error("invalid announcement signature")]
+    InvalidAnnouncementSignature,
+
+    /// Invoice terms are invalid
+    #[error("invalid invoice: {reason}")]
+    InvalidInvoice {
+        /// Reason the invoice is invalid
+        reason: String,
+    },
+
+    /// Invoice payee signature is invalid
+    #[error("invalid invoice signature")]
+    InvalidInvoiceSignature,
+
+    /// Canonical version pointer is invalid
+    #[error("invalid canonical version pointer: {reason}")]
+    InvalidCanonicalVersion {
+        /// Reason the pointer is invalid
+        reason: String,
+    },
+
+    /// Tombstone terms are invalid
+    #[error("invalid tombstone: {reason}")]
+    InvalidTombstone {
+        /// Reason the tombstone is invalid
+        reason: String,
+    },
+
+    /// Tombstone owner signature is invalid
+    #[error("invalid tombstone signature")]
+    InvalidTombstoneSignature,
+
+    /// Content report terms are invalid
+    #[error("invalid report: {reason}")]
+    InvalidReport {
+        /// Reason the report is invalid
+        reason: String,
+    },
+
+    /// Content report reporter signature is invalid
+    #[error("invalid report signature")]
+    InvalidReportSignature,
+
+    /// Revocation certificate terms are invalid
+    #[error("invalid revocation certificate: {reason}")]
+    InvalidRevocation {
+        /// Reason the certificate is invalid
+        reason: String,
+    },
+
+    /// Revocation certificate signature is invalid
+    #[error("invalid revocation certificate signature")]
+    InvalidRevocationSignature,
+
+    /// The sender or owner's identity key has been revoked
+    #[error("identity key revoked: {peer_id}")]
+    KeyRevoked {
+        /// The revoked peer
+        peer_id: nodalync_types::PeerId,
+    },
+
+    /// Fraud proof or delivery commitment terms are invalid
+    #[error("invalid fraud proof: {reason}")]
+    InvalidFraudProof {
+        /// Reason the proof is invalid
+        reason: String,
+    },
+
+    /// Fraud proof or delivery commitment signature is invalid
+    #[error("invalid fraud proof signature")]
+    InvalidFraudProofSignature,
+
+    /// Relay payment doesn't match the relayed layer
+    #[error("invalid relay payment: {reason}")]
+    InvalidRelayPayment {
+        /// Reason the payment is invalid
+        reason: String,
+    },
+
+    /// Query challenge response doesn't answer the outstanding challenge
+    #[error("invalid challenge response: {reason}")]
+    InvalidChallengeResponse {
+        /// Reason the response is invalid
+        reason: String,
+    },
+
+    /// Query challenge response signature is invalid
+    #[error("invalid challenge response signature")]
+    InvalidChallengeSignature,
+
+    /// DID document or advertised DID is invalid
+    #[error("invalid DID: {reason}")]
+    InvalidDid {
+        /// Reason the DID was rejected
+        reason: String,
+    },
+
+    /// Attribution certificate terms or proofs are invalid
+    #[error("invalid attribution certificate: {reason}")]
+    InvalidAttributionCertificate {
+        /// Reason the certificate is invalid
+        reason: String,
+    },
+
+    /// Attribution certificate signature is invalid
+    #[error("invalid attribution certificate signature")]
+    InvalidAttributionSignature,
+
+    /// State snapshot is invalid
+    #[error("invalid snapshot: {reason}")]
+    InvalidSnapshot {
+        /// Reason the snapshot is invalid
+        reason: String,
+    },
+
+    /// State snapshot issuer signature is invalid
+    #[error("invalid snapshot signature")]
+    InvalidSnapshotSignature,
+
+    /// Replica delegation is invalid
+    #[error("invalid replica delegation: {reason}")]
+    InvalidReplicaDelegation {
+        /// Reason the delegation is invalid
+        reason: String,
+    },
+
+    /// Replica catalog is invalid
+    #[error("invalid replica catalog: {reason}")]
+    InvalidReplicaCatalog {
+        /// Reason the catalog is invalid
+        reason: String,
+    },
+
+    /// Storefront is invalid
+    #[error("invalid storefront: {reason}")]
+    InvalidStorefront {
+        /// Reason the storefront is invalid
+        reason: String,
+    },
+
+    /// Storefront publisher signature is invalid
+    #[error("invalid storefront signature")]
+    InvalidStorefrontSignature,
+
     // =========================================================================
     // Access Validation Errors (§9.6)
     // =========================================================================
@@ -311,6 +567,108 @@ pub enum ValidationError {
         required: u64,
     },
 
+    /// Publisher's manifest claims less bond than its visibility requires
+    #[error("publisher bond of {required} required, {claimed} claimed")]
+    PublisherBondRequired {
+        /// Required bond amount
+        required: u64,
+        /// Bond claimed in the manifest
+        claimed: u64,
+    },
+
+    /// Publisher's claimed bond isn't posted on-chain
+    #[error("publisher bond of {required} is not posted on-chain")]
+    PublisherBondUnbacked {
+        /// Required bond amount
+        required: u64,
+    },
+
+    /// Content is under embargo until its publish time
+    #[error("content is embargoed until {publish_at}")]
+    Embargoed {
+        /// Scheduled publish time
+        publish_at: u64,
+    },
+
+    /// Requester has no free trial reads left (or none are offered)
+    #[error("no free trial reads left of {allowed}")]
+    TrialExhausted {
+        /// Free reads the trial offers each requester
+        allowed: u32,
+    },
+
+    /// Trial policy is invalid
+    #[error("invalid trial policy: {reason}")]
+    InvalidTrial {
+        /// Reason the policy is invalid
+        reason: String,
+    },
+
+    /// Requester has used up a daily quota
+    #[error("daily {limit} quota of {allowed} used up, resets at {resets_at}")]
+    QuotaExceeded {
+        /// Limit that was reached
+        limit: QuotaLimit,
+        /// Queries or bytes allowed each day
+        allowed: u64,
+        /// When the quota resets (start of the next UTC day)
+        resets_at: u64,
+    },
+
+    /// Quota policy is invalid
+    #[error("invalid quota policy: {reason}")]
+    InvalidQuota {
+        /// Reason the policy is invalid
+        reason: String,
+    },
+
+    /// Capability token does not grant this access
+    #[error("invalid capability: {reason}")]
+    InvalidCapability {
+        /// Reason the token is invalid
+        reason: String,
+    },
+
+    /// Capability token issuer signature is invalid
+    #[error("invalid capability signature")]
+    InvalidCapabilitySignature,
+
+    /// Capability token has expired
+    #[error("capability expired at {expires_at}")]
+    CapabilityExpired {
+        /// When the token expired
+        expires_at: u64,
+    },
+
+    /// Group membership list is invalid
+    #[error("invalid group: {reason}")]
+    InvalidGroup {
+        /// Reason the list is invalid
+        reason: String,
+    },
+
+    /// Group owner signature is invalid
+    #[error("invalid group signature")]
+    InvalidGroupSignature,
+
+    /// Re-download entitlement does not cover this content
+    #[error("invalid entitlement: {reason}")]
+    InvalidEntitlement {
+        /// Reason the entitlement is invalid
+        reason: String,
+    },
+
+    /// Entitlement receipt signature is invalid
+    #[error("invalid entitlement signature")]
+    InvalidEntitlementSignature,
+
+    /// Re-download entitlement window has passed
+    #[error("entitlement expired at {expires_at}")]
+    EntitlementExpired {
+        /// When the entitlement expired
+        expires_at: u64,
+    },
+
     // =========================================================================
     // L2 Entity Graph Validation Errors
     // =========================================================================
@@ -482,6 +840,62 @@ pub enum ValidationError {
     #[error("L2 content cannot be published (must remain private)")]
     L2CannotPublish,
 
+    // =========================================================================
+    // Collection Validation Errors
+    // =========================================================================
+    /// Collection has no items
+    #[error("collection must have at least one item")]
+    CollectionEmpty,
+
+    /// Collection has too many items
+    #[error("collection has too many items: {count} exceeds maximum {max}")]
+    CollectionTooManyItems {
+        /// Actual item count
+        count: usize,
+        /// Maximum allowed
+        max: usize,
+    },
+
+    /// Collection lists the same content twice
+    #[error("collection lists {hash} more than once")]
+    CollectionDuplicateItem {
+        /// The duplicated hash
+        hash: String,
+    },
+
+    /// Collection item weight is out of range
+    #[error("collection item {hash} has invalid weight {weight} (must be 1 to {max})")]
+    CollectionInvalidWeight {
+        /// Hash of the item
+        hash: String,
+        /// The invalid weight
+        weight: u32,
+        /// Maximum allowed weight
+        max: u32,
+    },
+
+    /// Collection item has invalid content type
+    #[error("collection item {hash} has invalid content type: {content_type}")]
+    CollectionInvalidItemType {
+        /// Hash of the item
+        hash: String,
+        /// The invalid content type
+        content_type: String,
+    },
+
+    /// Collection items don't match the manifest's derived_from
+    #[error("collection items do not match manifest derived_from")]
+    CollectionItemsMismatch,
+
+    /// Manifest price doesn't match the bundle price
+    #[error("collection price mismatch: bundle price is {expected}, manifest price is {actual}")]
+    CollectionPriceMismatch {
+        /// Bundle price from the collection
+        expected: u64,
+        /// Price in the manifest
+        actual: u64,
+    },
+
     // =========================================================================
     // Generic Errors
     // =========================================================================
@@ -512,6 +926,18 @@ impl ValidationError {
             Self::TagTooLong { .. } => ErrorCode::InvalidManifest,
             Self::ContentTooLarge { .. } => ErrorCode::ContentTooLarge,
 
+            // Structured metadata validation
+            Self::SchemaUriTooLong { .. }
+            | Self::FieldsTooLarge { .. }
+            | Self::FieldsWithoutSchema
+            | Self::UnknownSchema { .. }
+            | Self::InvalidSchema { .. }
+            | Self::InvalidFields { .. }
+            | Self::InvalidPreviewPolicy { .. }
+            | Self::InvalidChunkTree { .. }
+            | Self::InvalidSection { .. }
+            | Self::InvalidMetering { .. } => ErrorCode::InvalidManifest,
+
             // Version validation
             Self::V1HasPrevious
             | Self::V1RootMismatch
@@ -533,7 +959,8 @@ impl ValidationError {
             | Self::DepthMismatch { .. }
             | Self::SelfReference
             | Self::SelfRoot
-            | Self::DepthTooDeep { .. } => ErrorCode::InvalidProvenance,
+            | Self::DepthTooDeep { .. }
+            | Self::AttestationMismatch { .. } => ErrorCode::InvalidProvenance,
 
             // Payment validation
             Self::InsufficientPayment { .. } => ErrorCode::PaymentInvalid,
@@ -542,8 +969,11 @@ impl ValidationError {
             Self::ChannelNotOpen { .. } => ErrorCode::ChannelClosed,
             Self::InsufficientChannelBalance { .. } => ErrorCode::InsufficientBalance,
             Self::InvalidNonce { .. } => ErrorCode::InvalidNonce,
+            Self::ReplayedNonce { .. } => ErrorCode::InvalidNonce,
             Self::InvalidPaymentSignature => ErrorCode::InvalidSignature,
             Self::ProvenanceMismatch => ErrorCode::PaymentInvalid,
+            Self::InvalidReferral { .. } => ErrorCode::PaymentInvalid,
+            Self::InvalidReferralPolicy { .. } => ErrorCode::InvalidManifest,
 
             // Message validation
             Self::UnsupportedVersion { .. } => ErrorCode::InvalidManifest,
@@ -552,12 +982,53 @@ impl ValidationError {
             Self::InvalidSender => ErrorCode::InvalidManifest,
             Self::InvalidMessageSignature => ErrorCode::InvalidSignature,
             Self::PayloadDecodeFailed { .. } => ErrorCode::InvalidManifest,
+            Self::InvalidAnnouncementSignature => ErrorCode::InvalidSignature,
+            Self::InvalidInvoice { .. } => ErrorCode::PaymentInvalid,
+            Self::InvalidInvoiceSignature => ErrorCode::InvalidSignature,
+            Self::InvalidCanonicalVersion { .. } => ErrorCode::InvalidManifest,
+            Self::InvalidTombstone { .. } => ErrorCode::InvalidManifest,
+            Self::InvalidTombstoneSignature => ErrorCode::InvalidSignature,
+            Self::InvalidReport { .. } => ErrorCode::InvalidManifest,
+            Self::InvalidReportSignature => ErrorCode::InvalidSignature,
+            Self::InvalidRevocation { .. } => ErrorCode::InvalidManifest,
+            Self::InvalidRevocationSignature => ErrorCode::InvalidSignature,
+            Self::KeyRevoked { .. } => ErrorCode::AccessDenied,
+            Self::InvalidFraudProof { .. } => ErrorCode::InvalidManifest,
+            Self::InvalidFraudProofSignature => ErrorCode::InvalidSignature,
+            Self::InvalidRelayPayment { .. } => ErrorCode::PaymentInvalid,
+            Self::InvalidChallengeResponse { .. } => ErrorCode::AccessDenied,
+            Self::InvalidChallengeSignature => ErrorCode::InvalidSignature,
+            Self::InvalidDid { .. } => ErrorCode::InvalidManifest,
+            Self::InvalidAttributionCertificate { .. } => ErrorCode::InvalidManifest,
+            Self::InvalidAttributionSignature => ErrorCode::InvalidSignature,
+            Self::InvalidSnapshot { .. } => ErrorCode::InvalidManifest,
+            Self::InvalidSnapshotSignature => ErrorCode::InvalidSignature,
+            Self::InvalidReplicaDelegation { .. } => ErrorCode::InvalidManifest,
+            Self::InvalidReplicaCatalog { .. } => ErrorCode::InvalidManifest,
+            Self::InvalidStorefront { .. } => ErrorCode::InvalidManifest,
+            Self::InvalidStorefrontSignature => ErrorCode::InvalidSignature,
 
             // Access validation
-            Self::ContentPrivate | Self::NotInAllowlist | Self::InDenylist => {
-                ErrorCode::AccessDenied
+            Self::ContentPrivate
+            | Self::NotInAllowlist
+            | Self::InDenylist
+            | Self::Embargoed { .. }
+            | Self::InvalidCapability { .. }
+            | Self::CapabilityExpired { .. }
+            | Self::InvalidGroup { .. }
+            | Self::InvalidEntitlement { .. } => ErrorCode::AccessDenied,
+            Self::InvalidCapabilitySignature
+            | Self::InvalidGroupSignature
+            | Self::InvalidEntitlementSignature => ErrorCode::InvalidSignature,
+            Self::BondRequired { .. }
+            | Self::TrialExhausted { .. }
+            | Self::EntitlementExpired { .. } => ErrorCode::PaymentRequired,
+            Self::InvalidTrial { .. } => ErrorCode::InvalidManifest,
+            Self::QuotaExceeded { .. } => ErrorCode::RateLimited,
+            Self::InvalidQuota { .. } => ErrorCode::InvalidManifest,
+            Self::PublisherBondRequired { .. } | Self::PublisherBondUnbacked { .. } => {
+                ErrorCode::InvalidManifest
             }
-            Self::BondRequired { .. } => ErrorCode::PaymentRequired,
 
             // L2 validation
             Self::L2VisibilityNotPrivate { .. }
@@ -584,6 +1055,15 @@ impl ValidationError {
             Self::L2InvalidUri { .. } => ErrorCode::L2InvalidUri,
             Self::L2CannotPublish => ErrorCode::L2CannotPublish,
 
+            // Collection validation
+            Self::CollectionEmpty
+            | Self::CollectionTooManyItems { .. }
+            | Self::CollectionDuplicateItem { .. }
+            | Self::CollectionInvalidWeight { .. }
+            | Self::CollectionInvalidItemType { .. }
+            | Self::CollectionPriceMismatch { .. } => ErrorCode::InvalidManifest,
+            Self::CollectionItemsMismatch => ErrorCode::InvalidProvenance,
+
             // Generic
             Self::PublicKeyNotFound { .. } => ErrorCode::PeerNotFound,
             Self::Internal(_) => ErrorCode::InternalError,
@@ -591,6 +1071,12 @@ impl ValidationError {
     }
 }
 
+impl From<&ValidationError> for nodalync_types::ErrorCode {
+    fn from(error: &ValidationError) -> Self {
+        error.error_code()
+    }
+}
+
 /// Result type for validation operations.
 pub type ValidationResult<T> = std::result::Result<T, ValidationError>;
 
@@ -640,11 +1126,168 @@ mod tests {
             ValidationError::ContentPrivate.error_code(),
             ErrorCode::AccessDenied
         );
+        assert_eq!(
+            ErrorCode::from(&ValidationError::ContentPrivate),
+            ErrorCode::AccessDenied
+        );
 
         assert_eq!(
             ValidationError::InvalidPaymentSignature.error_code(),
             ErrorCode::InvalidSignature
         );
+
+        assert_eq!(
+            ValidationError::CapabilityExpired { expires_at: 1 }.error_code(),
+            ErrorCode::AccessDenied
+        );
+
+        assert_eq!(
+            ValidationError::InvalidGroupSignature.error_code(),
+            ErrorCode::InvalidSignature
+        );
+
+        assert_eq!(
+            ValidationError::InvalidTombstoneSignature.error_code(),
+            ErrorCode::InvalidSignature
+        );
+
+        assert_eq!(
+            ValidationError::InvalidReportSignature.error_code(),
+            ErrorCode::InvalidSignature
+        );
+
+        assert_eq!(
+            ValidationError::InvalidRevocationSignature.error_code(),
+            ErrorCode::InvalidSignature
+        );
+
+        assert_eq!(
+            ValidationError::InvalidChallengeSignature.error_code(),
+            ErrorCode::InvalidSignature
+        );
+
+        assert_eq!(
+            ValidationError::InvalidFraudProofSignature.error_code(),
+            ErrorCode::InvalidSignature
+        );
+
+        assert_eq!(
+            ValidationError::InvalidAttributionSignature.error_code(),
+            ErrorCode::InvalidSignature
+        );
+
+        assert_eq!(
+            ValidationError::PublisherBondUnbacked { required: 100 }.error_code(),
+            ErrorCode::InvalidManifest
+        );
+
+        assert_eq!(
+            ValidationError::InvalidPreviewPolicy {
+                reason: "bad".into()
+            }
+            .error_code(),
+            ErrorCode::InvalidManifest
+        );
+
+        assert_eq!(
+            ValidationError::InvalidChunkTree {
+                reason: "bad".into()
+            }
+            .error_code(),
+            ErrorCode::InvalidManifest
+        );
+
+        assert_eq!(
+            ValidationError::InvalidSection {
+                reason: "bad".into()
+            }
+            .error_code(),
+            ErrorCode::InvalidManifest
+        );
+
+        assert_eq!(
+            ValidationError::InvalidMetering {
+                reason: "bad".into()
+            }
+            .error_code(),
+            ErrorCode::InvalidManifest
+        );
+
+        assert_eq!(
+            ValidationError::InvalidReferral {
+                reason: "bad".into()
+            }
+            .error_code(),
+            ErrorCode::PaymentInvalid
+        );
+        assert_eq!(
+            ValidationError::InvalidReferralPolicy {
+                basis_points: 0,
+                max: 2_000
+            }
+            .error_code(),
+            ErrorCode::InvalidManifest
+        );
+
+        assert_eq!(
+            ValidationError::TrialExhausted { allowed: 3 }.error_code(),
+            ErrorCode::PaymentRequired
+        );
+        assert_eq!(
+            ValidationError::InvalidTrial {
+                reason: "bad".into()
+            }
+            .error_code(),
+            ErrorCode::InvalidManifest
+        );
+        assert_eq!(
+            ValidationError::QuotaExceeded {
+                limit: QuotaLimit::Queries,
+                allowed: 10,
+                resets_at: 86_400_000,
+            }
+            .error_code(),
+            ErrorCode::RateLimited
+        );
+        assert_eq!(
+            ValidationError::InvalidQuota {
+                reason: "bad".into()
+            }
+            .error_code(),
+            ErrorCode::InvalidManifest
+        );
+
+        assert_eq!(
+            ValidationError::InvalidEntitlement {
+                reason: "bad".into()
+            }
+            .error_code(),
+            ErrorCode::AccessDenied
+        );
+        assert_eq!(
+            ValidationError::InvalidEntitlementSignature.error_code(),
+            ErrorCode::InvalidSignature
+        );
+        assert_eq!(
+            ValidationError::EntitlementExpired { expires_at: 1 }.error_code(),
+            ErrorCode::PaymentRequired
+        );
+
+        assert_eq!(
+            ValidationError::InvalidDid {
+                reason: "bad".into()
+            }
+            .error_code(),
+            ErrorCode::InvalidManifest
+        );
+
+        assert_eq!(
+            ValidationError::AttestationMismatch {
+                reason: "bad".into()
+            }
+            .error_code(),
+            ErrorCode::InvalidProvenance
+        );
     }
 
     #[test]
@@ -0,0 +1,288 @@
+//! Delivery commitment and fraud proof validation.
+//!
+//! A provider commits to the hash of the bytes it delivers, bound to the
+//! requested content, the payment and the requester. A requester that
+//! receives bytes not matching the requested hash turns the commitment
+//! into a fraud proof. The proof stands on its own: both hashes are under
+//! the provider's signature, so checking it needs no content.
+
+use nodalync_crypto::{content_hash, peer_id_from_public_key, sign, verify, Hash, PrivateKey};
+use nodalync_types::{DeliveryCommitment, FraudProof, PeerId};
+
+use crate::error::{ValidationError, ValidationResult};
+
+/// Domain separation tag for delivery commitments.
+const DELIVERY_DOMAIN: &[u8] = b"nodalync:delivery";
+
+/// Construct the message a provider signs to commit to a delivery.
+///
+/// `H("nodalync:delivery" || content_hash || served_hash || payment_id || requester)`
+pub fn construct_delivery_message(
+    content: &Hash,
+    served_hash: &Hash,
+    payment_id: &Hash,
+    requester: &PeerId,
+) -> Hash {
+    content_hash(
+        &[
+            DELIVERY_DOMAIN,
+            content.0.as_slice(),
+            served_hash.0.as_slice(),
+            payment_id.0.as_slice(),
+            requester.0.as_slice(),
+        ]
+        .concat(),
+    )
+}
+
+/// Commit to delivering `served` bytes for a query of `content`, as the
+/// holder of `private_key`.
+pub fn sign_delivery(
+    private_key: &PrivateKey,
+    content: &Hash,
+    served: &[u8],
+    payment_id: &Hash,
+    requester: &PeerId,
+) -> DeliveryCommitment {
+    let served_hash = content_hash(served);
+    let message = construct_delivery_message(content, &served_hash, payment_id, requester);
+    DeliveryCommitment {
+        served_hash,
+        provider_key: private_key.public_key(),
+        signature: sign(private_key, &message.0),
+    }
+}
+
+/// Validate a provider's commitment to a delivery we received.
+///
+/// Checks:
+/// 1. `served_hash` is the hash of the bytes we received
+/// 2. `provider_key` belongs to `provider`
+/// 3. The signature over the delivery message verifies
+pub fn validate_delivery_commitment(
+    commitment: &DeliveryCommitment,
+    content: &Hash,
+    served: &[u8],
+    payment_id: &Hash,
+    provider: &PeerId,
+    requester: &PeerId,
+) -> ValidationResult<()> {
+    if commitment.served_hash != content_hash(served) {
+        return Err(invalid("commitment does not match the bytes served"));
+    }
+    verify_commitment(commitment, content, payment_id, provider, requester)
+}
+
+/// Sign a fraud proof as its requester.
+///
+/// Recomputes the hash from the terms before signing it.
+pub fn sign_fraud_proof(private_key: &PrivateKey, proof: &mut FraudProof) {
+    proof.hash = proof.compute_hash();
+    proof.signature = sign(private_key, &proof.hash.0);
+}
+
+/// Validate a fraud proof.
+///
+/// Checks:
+/// 1. `hash` matches the terms
+/// 2. The served hash differs from the requested hash
+/// 3. The provider's commitment verifies against the provider
+/// 4. `requester_key` belongs to `requester`
+/// 5. The requester's signature over `hash` verifies
+pub fn validate_fraud_proof(proof: &FraudProof) -> ValidationResult<()> {
+    if proof.hash != proof.compute_hash() {
+        return Err(invalid("hash does not match the proof terms"));
+    }
+
+    if proof.delivery.served_hash == proof.content_hash {
+        return Err(invalid("served content matches the requested hash"));
+    }
+
+    verify_commitment(
+        &proof.delivery,
+        &proof.content_hash,
+        &proof.payment_id,
+        &proof.provider,
+        &proof.requester,
+    )?;
+
+    if proof.requester != peer_id_from_public_key(&proof.requester_key) {
+        return Err(invalid("requester does not match public key"));
+    }
+
+    if !verify(&proof.requester_key, &proof.hash.0, &proof.signature) {
+        return Err(ValidationError::InvalidFraudProofSignature);
+    }
+
+    Ok(())
+}
+
+fn verify_commitment(
+    commitment: &DeliveryCommitment,
+    content: &Hash,
+    payment_id: &Hash,
+    provider: &PeerId,
+    requester: &PeerId,
+) -> ValidationResult<()> {
+    if *provider != peer_id_from_public_key(&commitment.provider_key) {
+        return Err(invalid("provider does not match public key"));
+    }
+
+    let message =
+        construct_delivery_message(content, &commitment.served_hash, payment_id, requester);
+    if !verify(&commitment.provider_key, &message.0, &commitment.signature) {
+        return Err(ValidationError::InvalidFraudProofSignature);
+    }
+
+    Ok(())
+}
+
+fn invalid(reason: impl Into<String>) -> ValidationError {
+    ValidationError::InvalidFraudProof {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::generate_identity;
+
+    struct Parties {
+        provider_key: PrivateKey,
+        provider: PeerId,
+        requester_key: PrivateKey,
+        requester: PeerId,
+    }
+
+    fn parties() -> Parties {
+        let (provider_key, provider_public) = generate_identity();
+        let (requester_key, requester_public) = generate_identity();
+        Parties {
+            provider: peer_id_from_public_key(&provider_public),
+            provider_key,
+            requester: peer_id_from_public_key(&requester_public),
+            requester_key,
+        }
+    }
+
+    fn proof_for(p: &Parties, served: &[u8]) -> FraudProof {
+        let content = content_hash(b"requested");
+        let payment_id = content_hash(b"payment");
+        let delivery = sign_delivery(&p.provider_key, &content, served, &payment_id, &p.requester);
+        let mut proof = FraudProof::new(
+            content,
+            payment_id,
+            p.provider,
+            delivery,
+            p.requester,
+            p.requester_key.public_key(),
+            1_000,
+        );
+        sign_fraud_proof(&p.requester_key, &mut proof);
+        proof
+    }
+
+    #[test]
+    fn test_delivery_commitment() {
+        let p = parties();
+        let content = content_hash(b"requested");
+        let payment_id = content_hash(b"payment");
+        let commitment = sign_delivery(
+            &p.provider_key,
+            &content,
+            b"requested",
+            &payment_id,
+            &p.requester,
+        );
+
+        assert!(validate_delivery_commitment(
+            &commitment,
+            &content,
+            b"requested",
+            &payment_id,
+            &p.provider,
+            &p.requester
+        )
+        .is_ok());
+
+        // Not what we received
+        assert!(matches!(
+            validate_delivery_commitment(
+                &commitment,
+                &content,
+                b"other",
+                &payment_id,
+                &p.provider,
+                &p.requester
+            ),
+            Err(ValidationError::InvalidFraudProof { .. })
+        ));
+
+        // Made for another requester
+        assert_eq!(
+            validate_delivery_commitment(
+                &commitment,
+                &content,
+                b"requested",
+                &payment_id,
+                &p.provider,
+                &p.provider
+            ),
+            Err(ValidationError::InvalidFraudProofSignature)
+        );
+    }
+
+    #[test]
+    fn test_valid_fraud_proof() {
+        let p = parties();
+        assert!(validate_fraud_proof(&proof_for(&p, b"garbage")).is_ok());
+    }
+
+    #[test]
+    fn test_fraud_proof_rejected() {
+        let p = parties();
+
+        // The provider served what was asked for
+        let honest = proof_for(&p, b"requested");
+        assert!(matches!(
+            validate_fraud_proof(&honest),
+            Err(ValidationError::InvalidFraudProof { .. })
+        ));
+
+        // Changed terms
+        let mut changed = proof_for(&p, b"garbage");
+        changed.created_at += 1;
+        assert!(matches!(
+            validate_fraud_proof(&changed),
+            Err(ValidationError::InvalidFraudProof { .. })
+        ));
+
+        // A served hash the provider never signed, re-signed by the requester
+        let mut forged = proof_for(&p, b"garbage");
+        forged.delivery.served_hash = content_hash(b"something else");
+        sign_fraud_proof(&p.requester_key, &mut forged);
+        assert_eq!(
+            validate_fraud_proof(&forged),
+            Err(ValidationError::InvalidFraudProofSignature)
+        );
+
+        // Blaming another provider with this one's commitment
+        let other = parties();
+        let mut blamed = proof_for(&p, b"garbage");
+        blamed.provider = other.provider;
+        sign_fraud_proof(&p.requester_key, &mut blamed);
+        assert!(matches!(
+            validate_fraud_proof(&blamed),
+            Err(ValidationError::InvalidFraudProof { .. })
+        ));
+
+        // Signed by someone other than the requester
+        let mut stolen = proof_for(&p, b"garbage");
+        sign_fraud_proof(&other.requester_key, &mut stolen);
+        assert_eq!(
+            validate_fraud_proof(&stolen),
+            Err(ValidationError::InvalidFraudProofSignature)
+        );
+    }
+}
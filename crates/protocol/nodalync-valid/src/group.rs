@@ -0,0 +1,185 @@
+//! Group membership validation.
+//!
+//! Group membership lists are signed by the group owner over the list
+//! hash, and carry the owner's public key. Validation checks the limits,
+//! that the ID and hash match the terms, that the key belongs to the
+//! owner, and the signature. Whether a list is newer than a cached one is
+//! up to the caller.
+//!
+//! Access validation resolves the groups named in `AccessControl::groups`
+//! through a [`GroupResolver`].
+
+use std::collections::{HashMap, HashSet};
+
+use nodalync_crypto::{peer_id_from_public_key, sign, verify, Hash, PrivateKey};
+use nodalync_types::{Group, PeerId, MAX_GROUP_MEMBERS, MAX_GROUP_NAME_LENGTH};
+
+use crate::error::{ValidationError, ValidationResult};
+
+/// Callback trait for resolving group membership during access checks.
+pub trait GroupResolver {
+    /// Check if a peer is a member of the group with this ID.
+    ///
+    /// Groups that cannot be resolved have no members.
+    fn is_member(&self, group: &Hash, peer: &PeerId) -> bool;
+}
+
+/// Resolve against verified membership lists keyed by group ID.
+impl GroupResolver for HashMap<Hash, Group> {
+    fn is_member(&self, group: &Hash, peer: &PeerId) -> bool {
+        self.get(group).is_some_and(|group| group.is_member(peer))
+    }
+}
+
+/// Sign a group membership list as its owner.
+///
+/// Recomputes the ID and hash from the terms before signing.
+pub fn sign_group(private_key: &PrivateKey, group: &mut Group) {
+    group.id = Group::compute_id(&group.owner, &group.name);
+    group.hash = group.compute_hash();
+    group.signature = sign(private_key, &group.hash.0);
+}
+
+/// Validate a group membership list and its owner signature.
+///
+/// Checks:
+/// 1. `name` is non-empty and at most `MAX_GROUP_NAME_LENGTH` bytes
+/// 2. At most `MAX_GROUP_MEMBERS` members, without duplicates
+/// 3. `id` and `hash` match the terms
+/// 4. `owner` is derived from `owner_key`
+/// 5. The signature over `hash` verifies against `owner_key`
+pub fn validate_group(group: &Group) -> ValidationResult<()> {
+    if group.name.is_empty() {
+        return Err(invalid("name is empty"));
+    }
+    if group.name.len() > MAX_GROUP_NAME_LENGTH {
+        return Err(invalid(format!(
+            "name is {} bytes, max is {}",
+            group.name.len(),
+            MAX_GROUP_NAME_LENGTH
+        )));
+    }
+
+    if group.members.len() > MAX_GROUP_MEMBERS {
+        return Err(invalid(format!(
+            "{} members, max is {}",
+            group.members.len(),
+            MAX_GROUP_MEMBERS
+        )));
+    }
+    let mut seen = HashSet::with_capacity(group.members.len());
+    if !group.members.iter().all(|member| seen.insert(member)) {
+        return Err(invalid("duplicate member"));
+    }
+
+    if group.id != Group::compute_id(&group.owner, &group.name) {
+        return Err(invalid("id does not match owner and name"));
+    }
+    if group.hash != group.compute_hash() {
+        return Err(invalid("hash does not match the group terms"));
+    }
+
+    if group.owner != peer_id_from_public_key(&group.owner_key) {
+        return Err(invalid("owner does not match owner key"));
+    }
+
+    if !verify(&group.owner_key, &group.hash.0, &group.signature) {
+        return Err(ValidationError::InvalidGroupSignature);
+    }
+
+    Ok(())
+}
+
+fn invalid(reason: impl Into<String>) -> ValidationError {
+    ValidationError::InvalidGroup {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::generate_identity;
+
+    fn create_signed_group(members: Vec<PeerId>) -> (Group, PrivateKey) {
+        let (private_key, public_key) = generate_identity();
+        let mut group = Group::new(
+            peer_id_from_public_key(&public_key),
+            public_key,
+            "research",
+            members,
+            1,
+            1_000,
+        );
+        sign_group(&private_key, &mut group);
+        (group, private_key)
+    }
+
+    #[test]
+    fn test_valid_group() {
+        let (group, _) = create_signed_group(vec![PeerId([1u8; 20]), PeerId([2u8; 20])]);
+        assert!(validate_group(&group).is_ok());
+
+        let (empty, _) = create_signed_group(vec![]);
+        assert!(validate_group(&empty).is_ok());
+    }
+
+    #[test]
+    fn test_group_limits() {
+        let (mut group, _) = create_signed_group(vec![]);
+
+        group.members = vec![PeerId([1u8; 20]), PeerId([1u8; 20])];
+        assert!(matches!(
+            validate_group(&group),
+            Err(ValidationError::InvalidGroup { .. })
+        ));
+
+        group.members = vec![];
+        group.name = "x".repeat(MAX_GROUP_NAME_LENGTH + 1);
+        assert!(validate_group(&group).is_err());
+
+        group.name = String::new();
+        assert!(validate_group(&group).is_err());
+    }
+
+    #[test]
+    fn test_group_tampering() {
+        let (group, private_key) = create_signed_group(vec![PeerId([1u8; 20])]);
+
+        // Adding a member without re-signing changes the hash
+        let mut changed = group.clone();
+        changed.members.push(PeerId([2u8; 20]));
+        assert!(validate_group(&changed).is_err());
+
+        // Re-hashed but not re-signed
+        changed.hash = changed.compute_hash();
+        assert!(matches!(
+            validate_group(&changed),
+            Err(ValidationError::InvalidGroupSignature)
+        ));
+
+        // Signed with another key claiming the owner's identity
+        let (other_key, other_public) = generate_identity();
+        let mut forged = group.clone();
+        forged.owner_key = other_public;
+        sign_group(&other_key, &mut forged);
+        assert!(validate_group(&forged).is_err());
+
+        // The owner re-signing is fine
+        changed.version = 2;
+        sign_group(&private_key, &mut changed);
+        assert!(validate_group(&changed).is_ok());
+    }
+
+    #[test]
+    fn test_group_resolver() {
+        let member = PeerId([1u8; 20]);
+        let (group, _) = create_signed_group(vec![member]);
+        let resolver: HashMap<Hash, Group> = [(group.id, group.clone())].into();
+
+        assert!(resolver.is_member(&group.id, &member));
+        assert!(resolver.is_member(&group.id, &group.owner));
+        assert!(!resolver.is_member(&group.id, &PeerId([2u8; 20])));
+        assert!(!resolver.is_member(&Hash([0u8; 32]), &member));
+    }
+}
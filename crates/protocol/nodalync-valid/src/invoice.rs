@@ -0,0 +1,287 @@
+//! Invoice validation.
+//!
+//! Invoices are signed by the payee over the invoice hash, and carry the
+//! payee's public key. Validation checks the terms, that the hash matches
+//! them, that the key belongs to the payee, and the signature. Expiry is
+//! checked separately by the payer and payee against their own clocks.
+//!
+//! Invoices are paid through the payment channel between payer and payee,
+//! with a payment whose query hash is the invoice hash.
+
+use nodalync_crypto::{peer_id_from_public_key, sign, verify, PrivateKey, PublicKey};
+use nodalync_types::{Channel, ChannelState, Invoice, Payment, MAX_INVOICE_MEMO_LENGTH};
+
+use crate::error::{ValidationError, ValidationResult};
+use crate::payment::{validate_payment_nonce, verify_payment_signature};
+
+/// Sign an invoice as its payee.
+///
+/// Recomputes the hash from the terms before signing it.
+pub fn sign_invoice(private_key: &PrivateKey, invoice: &mut Invoice) {
+    invoice.hash = invoice.compute_hash();
+    invoice.signature = sign(private_key, &invoice.hash.0);
+}
+
+/// Validate an invoice's terms and payee signature.
+///
+/// Checks:
+/// 1. `amount > 0`
+/// 2. `memo.len() <= MAX_INVOICE_MEMO_LENGTH`
+/// 3. `expires_at > created_at`
+/// 4. `hash` matches the terms
+/// 5. `payee` is derived from `payee_key`
+/// 6. The signature over `hash` verifies against `payee_key`
+pub fn validate_invoice(invoice: &Invoice) -> ValidationResult<()> {
+    if invoice.amount == 0 {
+        return Err(invalid("amount must be greater than zero"));
+    }
+
+    if invoice.memo.len() > MAX_INVOICE_MEMO_LENGTH {
+        return Err(invalid(format!(
+            "memo is {} bytes, max is {}",
+            invoice.memo.len(),
+            MAX_INVOICE_MEMO_LENGTH
+        )));
+    }
+
+    if invoice.expires_at <= invoice.created_at {
+        return Err(invalid("expires before it was created"));
+    }
+
+    if invoice.hash != invoice.compute_hash() {
+        return Err(invalid("hash does not match the invoice terms"));
+    }
+
+    if invoice.payee != peer_id_from_public_key(&invoice.payee_key) {
+        return Err(invalid("payee does not match payee key"));
+    }
+
+    if !verify(&invoice.payee_key, &invoice.hash.0, &invoice.signature) {
+        return Err(ValidationError::InvalidInvoiceSignature);
+    }
+
+    Ok(())
+}
+
+/// Validate a channel payment for an invoice, as its payee.
+///
+/// Checks:
+/// 1. `payment.amount >= invoice.amount`
+/// 2. `payment.recipient == invoice.payee`
+/// 3. `payment.query_hash == invoice.hash`
+/// 4. The payment is for this channel, which is open
+/// 5. The payer's channel balance covers the payment
+/// 6. The nonce strictly increases (see [`validate_payment_nonce`])
+/// 7. The payment signature verifies (if the payer's key is known)
+pub fn validate_invoice_payment(
+    payment: &Payment,
+    invoice: &Invoice,
+    channel: &Channel,
+    payer_pubkey: Option<&PublicKey>,
+    payment_nonce: u64,
+    last_nonce: Option<u64>,
+) -> ValidationResult<()> {
+    if payment.amount < invoice.amount {
+        return Err(ValidationError::InsufficientPayment {
+            amount: payment.amount,
+            price: invoice.amount,
+        });
+    }
+
+    if payment.recipient != invoice.payee {
+        return Err(ValidationError::WrongRecipient {
+            payment_recipient: format!("{}", payment.recipient),
+            owner: format!("{}", invoice.payee),
+        });
+    }
+
+    if payment.query_hash != invoice.hash {
+        return Err(ValidationError::QueryHashMismatch);
+    }
+
+    if payment.channel_id != channel.channel_id {
+        return Err(invalid("payment is for a different channel"));
+    }
+
+    if channel.state != ChannelState::Open {
+        return Err(ValidationError::ChannelNotOpen {
+            state: format!("{:?}", channel.state),
+        });
+    }
+
+    if channel.their_balance < payment.amount {
+        return Err(ValidationError::InsufficientChannelBalance {
+            balance: channel.their_balance,
+            amount: payment.amount,
+        });
+    }
+
+    validate_payment_nonce(payment_nonce, channel.nonce, last_nonce)?;
+
+    if let Some(pubkey) = payer_pubkey {
+        if !verify_payment_signature(pubkey, payment) {
+            return Err(ValidationError::InvalidPaymentSignature);
+        }
+    }
+
+    Ok(())
+}
+
+fn invalid(reason: impl Into<String>) -> ValidationError {
+    ValidationError::InvalidInvoice {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payment::construct_payment_message;
+    use nodalync_crypto::{content_hash, generate_identity, Signature};
+
+    fn create_signed_invoice() -> (Invoice, PrivateKey) {
+        let (private_key, public_key) = generate_identity();
+        let mut invoice = Invoice::new(
+            peer_id_from_public_key(&public_key),
+            public_key,
+            500,
+            "Climate dataset",
+            Some(content_hash(b"dataset")),
+            1_000,
+            2_000,
+        );
+        sign_invoice(&private_key, &mut invoice);
+        (invoice, private_key)
+    }
+
+    fn create_invoice_payment(
+        invoice: &Invoice,
+        channel: &Channel,
+        amount: u64,
+        payer_key: &PrivateKey,
+    ) -> Payment {
+        let mut payment = Payment::new(
+            content_hash(b"payment"),
+            channel.channel_id,
+            amount,
+            invoice.payee,
+            invoice.hash,
+            vec![],
+            1_500,
+            Signature::from_bytes([0u8; 64]),
+        );
+        payment.signature = sign(payer_key, &construct_payment_message(&payment));
+        payment
+    }
+
+    #[test]
+    fn test_valid_invoice() {
+        let (invoice, _) = create_signed_invoice();
+        assert!(validate_invoice(&invoice).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_invoice_terms() {
+        let (invoice, private_key) = create_signed_invoice();
+
+        let mut zero = invoice.clone();
+        zero.amount = 0;
+        sign_invoice(&private_key, &mut zero);
+        assert!(matches!(
+            validate_invoice(&zero),
+            Err(ValidationError::InvalidInvoice { .. })
+        ));
+
+        let mut long_memo = invoice.clone();
+        long_memo.memo = "x".repeat(MAX_INVOICE_MEMO_LENGTH + 1);
+        sign_invoice(&private_key, &mut long_memo);
+        assert!(validate_invoice(&long_memo).is_err());
+
+        let mut backwards = invoice.clone();
+        backwards.expires_at = backwards.created_at;
+        sign_invoice(&private_key, &mut backwards);
+        assert!(validate_invoice(&backwards).is_err());
+    }
+
+    #[test]
+    fn test_tampered_invoice_fails() {
+        let (invoice, _) = create_signed_invoice();
+
+        // Changed terms no longer match the signed hash
+        let mut raised = invoice.clone();
+        raised.amount = 5_000;
+        assert!(matches!(
+            validate_invoice(&raised),
+            Err(ValidationError::InvalidInvoice { .. })
+        ));
+
+        // Re-hashed terms don't match the signature
+        raised.hash = raised.compute_hash();
+        assert!(matches!(
+            validate_invoice(&raised),
+            Err(ValidationError::InvalidInvoiceSignature)
+        ));
+
+        // Signed by someone else
+        let (other_key, _) = generate_identity();
+        let mut forged = invoice.clone();
+        sign_invoice(&other_key, &mut forged);
+        assert!(matches!(
+            validate_invoice(&forged),
+            Err(ValidationError::InvalidInvoiceSignature)
+        ));
+    }
+
+    #[test]
+    fn test_validate_invoice_payment() {
+        let (invoice, _) = create_signed_invoice();
+        let (payer_key, payer_pubkey) = generate_identity();
+        let mut channel = Channel::new(
+            content_hash(b"channel"),
+            peer_id_from_public_key(&payer_pubkey),
+            1_000,
+            1_000,
+        );
+        channel.mark_open(1_000, 1_000);
+
+        let payment = create_invoice_payment(&invoice, &channel, 500, &payer_key);
+        assert!(validate_invoice_payment(
+            &payment,
+            &invoice,
+            &channel,
+            Some(&payer_pubkey),
+            1,
+            None
+        )
+        .is_ok());
+
+        // Replayed nonce
+        assert!(matches!(
+            validate_invoice_payment(&payment, &invoice, &channel, None, 1, Some(1)),
+            Err(ValidationError::ReplayedNonce { .. })
+        ));
+
+        // Underpaid
+        let short = create_invoice_payment(&invoice, &channel, 499, &payer_key);
+        assert!(matches!(
+            validate_invoice_payment(&short, &invoice, &channel, None, 1, None),
+            Err(ValidationError::InsufficientPayment { .. })
+        ));
+
+        // Paying a different invoice
+        let mut other = payment.clone();
+        other.query_hash = content_hash(b"other invoice");
+        assert!(matches!(
+            validate_invoice_payment(&other, &invoice, &channel, None, 1, None),
+            Err(ValidationError::QueryHashMismatch)
+        ));
+
+        // Signed by someone other than the payer
+        let (other_key, _) = generate_identity();
+        let forged = create_invoice_payment(&invoice, &channel, 500, &other_key);
+        assert!(matches!(
+            validate_invoice_payment(&forged, &invoice, &channel, Some(&payer_pubkey), 1, None),
+            Err(ValidationError::InvalidPaymentSignature)
+        ));
+    }
+}
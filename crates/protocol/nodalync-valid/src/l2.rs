@@ -330,6 +330,12 @@ pub fn validate_l2_provenance(manifest: &Manifest, sources: &[Manifest]) -> Vali
                     content_type: "L3".to_string(),
                 });
             }
+            ContentType::Collection => {
+                return Err(ValidationError::L2InvalidSourceType {
+                    hash: format!("{}", source.hash),
+                    content_type: "Collection".to_string(),
+                });
+            }
             _ => {
                 return Err(ValidationError::Internal(
                     "unknown content type in L2 source".to_string(),
@@ -7,11 +7,31 @@
 //! # Validation Categories
 //!
 //! - **Content Validation** (§9.1): Hash, size, and metadata constraints
-//! - **Version Validation** (§9.2): Version chain rules
+//! - **Version Validation** (§9.2): Version chain rules, and forks against known versions
+//! - **Canonical Version Validation**: Owner-signed pointers choosing among forked versions
 //! - **Provenance Validation** (§9.3): Derivation and depth rules
-//! - **Payment Validation** (§9.4): Amount, channel, and signature rules
+//! - **Payment Validation** (§9.4): Amount, channel, signature and referral rules
 //! - **Message Validation** (§9.5): Protocol version, timestamp, and signature rules
-//! - **Access Validation** (§9.6): Visibility, allowlist/denylist, and bond rules
+//! - **Announcement Validation**: Publisher or replica signature over announcements
+//! - **Invoice Validation**: Invoice terms and payee signature
+//! - **Relay Validation**: Channel payments for relaying a query
+//! - **Capability Validation**: Owner-signed tokens granting access to one content hash
+//! - **Group Validation**: Owner-signed group membership lists
+//! - **Tombstone Validation**: Owner-signed withdrawals of content
+//! - **Report Validation**: Reporter-signed content reports
+//! - **Revocation Validation**: Self-signed key revocations, and rejecting revoked keys
+//! - **Challenge Validation**: Requesters' signed answers to query challenges
+//! - **Fraud Proof Validation**: Providers' delivery commitments and requesters' fraud proofs
+//! - **DID Validation**: `did:key` documents and the DIDs peers advertise
+//! - **Attribution Validation**: Owner-signed attribution certificates for derived content
+//! - **Snapshot Validation**: Issuer-signed snapshots of announcements and peers
+//! - **Replica Validation**: Primary-signed replica delegations and catalogs
+//! - **Storefront Validation**: Publisher-signed catalogs listed in the DHT
+//! - **Entitlement Validation**: Receipts presented to re-download bought content
+//! - **Access Validation** (§9.6): Visibility, allowlist/denylist, group, bond, embargo, trial and quota rules
+//! - **Publisher Bond Validation**: Bonds claimed by publishers, checked against visibility
+//! - **Collection Validation**: Item, weight and bundle price rules
+//! - **Structured Metadata Validation**: Fields checked against their schema
 //!
 //! # Usage
 //!
@@ -87,12 +107,32 @@
 //! ```
 
 pub mod access;
+pub mod announce;
+pub mod attribution;
+pub mod bond;
+pub mod canonical;
+pub mod capability;
+pub mod challenge;
+pub mod collection;
 pub mod content;
+pub mod did;
+pub mod entitlement;
 pub mod error;
+pub mod fraud;
+pub mod group;
+pub mod invoice;
 pub mod l2;
 pub mod message;
 pub mod payment;
 pub mod provenance;
+pub mod relay;
+pub mod replica;
+pub mod report;
+pub mod revocation;
+pub mod schema;
+pub mod snapshot;
+pub mod storefront;
+pub mod tombstone;
 pub mod validator;
 pub mod version;
 
@@ -101,20 +141,55 @@ pub use error::{ValidationError, ValidationResult};
 
 // Re-export standalone validation functions
 pub use access::{
-    is_owner, validate_access, validate_access_basic, validate_access_with_owner_bypass,
+    is_owner, validate_access, validate_access_basic, validate_access_with_groups,
+    validate_access_with_owner_bypass, validate_embargo, validate_quota, validate_quota_policy,
+    validate_trial, validate_trial_policy,
 };
-pub use content::{validate_content, validate_metadata};
+pub use announce::{construct_announce_message, sign_announcement, validate_announcement};
+pub use attribution::{sign_attribution_certificate, validate_attribution_certificate};
+pub use bond::{validate_publisher_bond, BondRequirements};
+pub use canonical::{sign_canonical_version, validate_canonical_version};
+pub use capability::{sign_capability, validate_capability};
+pub use challenge::{construct_challenge_message, sign_challenge, validate_challenge_response};
+pub use collection::validate_collection;
+pub use content::{validate_content, validate_metadata, validate_section_slice};
+pub use did::{validate_did_document, validate_peer_info};
+pub use entitlement::{receipt_payment_id, validate_entitlement};
+pub use fraud::{
+    construct_delivery_message, sign_delivery, sign_fraud_proof, validate_delivery_commitment,
+    validate_fraud_proof,
+};
+pub use group::{sign_group, validate_group, GroupResolver};
+pub use invoice::{sign_invoice, validate_invoice, validate_invoice_payment};
 pub use l2::{
     expand_curie, is_valid_uri, validate_l2_content, validate_l2_provenance, validate_l2_publish,
 };
 pub use message::{is_valid_message_type, validate_message, validate_message_basic};
 pub use payment::{
     construct_close_message, construct_payment_message, construct_receipt_message,
-    sign_channel_close, validate_payment, validate_payment_basic, verify_channel_close_signature,
-    BondChecker, PublicKeyLookup,
+    sign_channel_close, validate_free_query, validate_payment, validate_payment_basic,
+    validate_payment_nonce, validate_referral, validate_referral_policy,
+    verify_channel_close_signature, BondChecker, PublicKeyLookup,
 };
 pub use provenance::validate_provenance;
-pub use version::validate_version;
+pub use relay::validate_relay_payment;
+pub use replica::{
+    construct_replica_catalog_message, sign_replica_catalog, sign_replica_delegation,
+    validate_replica_catalog, validate_replica_delegation,
+};
+pub use report::{sign_report, validate_report};
+pub use revocation::{
+    sign_revocation, validate_manifest_not_revoked, validate_message_not_revoked,
+    validate_not_revoked, validate_revocation, RevocationList,
+};
+pub use schema::{
+    builtin_schema, validate_schema, validate_structured_metadata, CITATION_SCHEMA_URI,
+    DATASET_SCHEMA_URI, IPFS_SCHEMA_URI,
+};
+pub use snapshot::{construct_snapshot_message, sign_snapshot, validate_snapshot};
+pub use storefront::{construct_storefront_message, sign_storefront, validate_storefront};
+pub use tombstone::{sign_tombstone, validate_tombstone};
+pub use version::{find_version_forks, validate_version, VersionLookup};
 
 // Re-export validator trait and implementations
 pub use validator::{
@@ -7,9 +7,13 @@
 //! - Nonce validity
 //! - Signature verification
 //! - Provenance matching
+//! - Referrals against the content's referral policy
 
 use nodalync_crypto::{sign, verify, Hash, PrivateKey, PublicKey, Signature};
-use nodalync_types::{Amount, Channel, ChannelState, Manifest, Payment, PeerId, ProvenanceEntry};
+use nodalync_types::{
+    Amount, Channel, ChannelState, Manifest, Payment, PeerId, ProvenanceEntry, ReferralPolicy,
+    MAX_REFERRAL_BASIS_POINTS,
+};
 
 use crate::error::{ValidationError, ValidationResult};
 
@@ -35,7 +39,7 @@ pub trait BondChecker {
 /// 3. `query_hash == manifest.hash`
 /// 4. `channel.state == Open`
 /// 5. `channel.their_balance >= amount`
-/// 6. Payment nonce > channel nonce (replay prevention)
+/// 6. Payment nonce > channel nonce and > last recorded nonce (replay prevention)
 /// 7. Signature is valid
 /// 8. Provenance matches manifest
 ///
@@ -46,6 +50,7 @@ pub trait BondChecker {
 /// * `manifest` - The manifest for the queried content
 /// * `payer_pubkey` - The payer's public key for signature verification
 /// * `payment_nonce` - The payment's nonce value
+/// * `last_nonce` - Highest nonce already accepted on the channel, if tracked
 ///
 /// # Returns
 ///
@@ -56,6 +61,7 @@ pub fn validate_payment(
     manifest: &Manifest,
     payer_pubkey: Option<&PublicKey>,
     payment_nonce: u64,
+    last_nonce: Option<u64>,
 ) -> ValidationResult<()> {
     // 1. Amount sufficient
     if payment.amount < manifest.economics.price {
@@ -94,12 +100,7 @@ pub fn validate_payment(
     }
 
     // 6. Valid nonce (prevents replay)
-    if payment_nonce <= channel.nonce {
-        return Err(ValidationError::InvalidNonce {
-            nonce: payment_nonce,
-            channel_nonce: channel.nonce,
-        });
-    }
+    validate_payment_nonce(payment_nonce, channel.nonce, last_nonce)?;
 
     // 7. Verify signature (if public key provided)
     if let Some(pubkey) = payer_pubkey {
@@ -116,6 +117,58 @@ pub fn validate_payment(
     Ok(())
 }
 
+/// Validate a query that carries no payment.
+///
+/// Only free content (`manifest.economics.price == 0`) may be queried
+/// without a `Payment`; such queries bypass channel and settlement handling.
+pub fn validate_free_query(manifest: &Manifest) -> ValidationResult<()> {
+    if manifest.economics.price > 0 {
+        return Err(ValidationError::InsufficientPayment {
+            amount: 0,
+            price: manifest.economics.price,
+        });
+    }
+    Ok(())
+}
+
+/// Validate the referrer named by a paid query.
+///
+/// The content must offer a referral cut, and neither the requester nor the
+/// owner may refer the query: the first would be a discount, the second a
+/// bigger share at the expense of the root contributors.
+pub fn validate_referral(
+    manifest: &Manifest,
+    referrer: &PeerId,
+    requester: &PeerId,
+) -> ValidationResult<()> {
+    let reason = if manifest.economics.referral.is_none() {
+        "content offers no referral cut"
+    } else if referrer == requester {
+        "requester can't refer its own query"
+    } else if *referrer == manifest.owner {
+        "owner can't refer queries for its own content"
+    } else {
+        return Ok(());
+    };
+    Err(ValidationError::InvalidReferral {
+        reason: reason.to_string(),
+    })
+}
+
+/// Validate a referral policy an owner offers.
+///
+/// The cut must be at least one basis point and at most
+/// [`MAX_REFERRAL_BASIS_POINTS`].
+pub fn validate_referral_policy(policy: &ReferralPolicy) -> ValidationResult<()> {
+    if policy.basis_points == 0 || policy.basis_points > MAX_REFERRAL_BASIS_POINTS {
+        return Err(ValidationError::InvalidReferralPolicy {
+            basis_points: policy.basis_points,
+            max: MAX_REFERRAL_BASIS_POINTS,
+        });
+    }
+    Ok(())
+}
+
 /// Validate payment without signature verification.
 ///
 /// Use this for quick validation when the signature has already been verified
@@ -126,13 +179,43 @@ pub fn validate_payment_basic(
     manifest: &Manifest,
     payment_nonce: u64,
 ) -> ValidationResult<()> {
-    validate_payment(payment, channel, manifest, None, payment_nonce)
+    validate_payment(payment, channel, manifest, None, payment_nonce, None)
+}
+
+/// Validate that a payment nonce strictly increases.
+///
+/// The nonce must be greater than the channel nonce and, when the store
+/// tracks one, greater than the highest nonce already accepted on the
+/// channel. The tracked nonce catches replays after a crash left the
+/// channel row behind the payments it had already accepted.
+pub fn validate_payment_nonce(
+    payment_nonce: u64,
+    channel_nonce: u64,
+    last_nonce: Option<u64>,
+) -> ValidationResult<()> {
+    if payment_nonce <= channel_nonce {
+        return Err(ValidationError::InvalidNonce {
+            nonce: payment_nonce,
+            channel_nonce,
+        });
+    }
+
+    if let Some(last_nonce) = last_nonce {
+        if payment_nonce <= last_nonce {
+            return Err(ValidationError::ReplayedNonce {
+                nonce: payment_nonce,
+                last_nonce,
+            });
+        }
+    }
+
+    Ok(())
 }
 
 /// Verify a payment signature.
 ///
 /// The signature covers the payment data (excluding the signature itself).
-fn verify_payment_signature(pubkey: &PublicKey, payment: &Payment) -> bool {
+pub(crate) fn verify_payment_signature(pubkey: &PublicKey, payment: &Payment) -> bool {
     // Construct the message that was signed
     // This should match the signing process in nodalync-ops
     let message = construct_payment_message(payment);
@@ -315,6 +398,47 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_free_query_without_payment() {
+        let free = create_test_manifest(b"Free content", 0);
+        assert!(validate_free_query(&free).is_ok());
+
+        let paid = create_test_manifest(b"Content", 100);
+        assert!(matches!(
+            validate_free_query(&paid),
+            Err(ValidationError::InsufficientPayment {
+                amount: 0,
+                price: 100
+            })
+        ));
+    }
+
+    #[test]
+    fn test_referral() {
+        let mut manifest = create_test_manifest(b"content", 100);
+        let requester = test_peer_id();
+        let referrer = test_peer_id();
+        assert!(matches!(
+            validate_referral(&manifest, &referrer, &requester),
+            Err(ValidationError::InvalidReferral { .. })
+        ));
+
+        manifest.economics.referral = Some(ReferralPolicy::new(500));
+        assert!(validate_referral(&manifest, &referrer, &requester).is_ok());
+        assert!(validate_referral(&manifest, &requester, &requester).is_err());
+        assert!(validate_referral(&manifest, &manifest.owner, &requester).is_err());
+
+        assert!(validate_referral_policy(&ReferralPolicy::new(1)).is_ok());
+        assert!(validate_referral_policy(&ReferralPolicy::new(MAX_REFERRAL_BASIS_POINTS)).is_ok());
+        assert!(matches!(
+            validate_referral_policy(&ReferralPolicy::new(0)),
+            Err(ValidationError::InvalidReferralPolicy { .. })
+        ));
+        assert!(
+            validate_referral_policy(&ReferralPolicy::new(MAX_REFERRAL_BASIS_POINTS + 1)).is_err()
+        );
+    }
+
     #[test]
     fn test_payment_exceeds_price_ok() {
         let manifest = create_test_manifest(b"Content", 100);
@@ -409,6 +533,27 @@ mod tests {
         assert!(matches!(result, Err(ValidationError::InvalidNonce { .. })));
     }
 
+    #[test]
+    fn test_replayed_nonce_above_channel_nonce_fails() {
+        let manifest = create_test_manifest(b"Content", 100);
+        let mut channel = create_test_channel(manifest.owner, 1000);
+        channel.nonce = 2;
+        let (payment, _) = create_test_payment(&manifest, &channel, 100);
+
+        // Channel row is behind: nonce 4 was already accepted before a crash
+        let result = validate_payment(&payment, &channel, &manifest, None, 4, Some(4));
+        assert!(matches!(
+            result,
+            Err(ValidationError::ReplayedNonce {
+                nonce: 4,
+                last_nonce: 4
+            })
+        ));
+
+        assert!(validate_payment(&payment, &channel, &manifest, None, 5, Some(4)).is_ok());
+        assert!(validate_payment_nonce(3, 2, None).is_ok());
+    }
+
     #[test]
     fn test_provenance_mismatch() {
         let manifest = create_test_manifest(b"Content", 100);
@@ -3,6 +3,8 @@
 //! This module validates provenance chains:
 //! - L0: self-referential provenance
 //! - L3: derived from sources with correct root computation
+//! - Collection: derived from its items (weighted roots are checked by
+//!   `validate_collection`)
 //! - Depth constraints
 //! - No self-references
 
@@ -30,6 +32,10 @@ use crate::error::{ValidationError, ValidationResult};
 /// - `depth` must equal `max(sources.depth) + 1`
 /// - No self-reference (content hash not in `derived_from` or `root_l0l1`)
 ///
+/// For Collection content the same rules apply, except `root_l0l1` is
+/// weighted by the collection's items and can only be checked against the
+/// collection itself (see `validate_collection`).
+///
 /// # Arguments
 ///
 /// * `manifest` - The manifest to validate
@@ -57,6 +63,10 @@ pub fn validate_provenance(manifest: &Manifest, sources: &[Manifest]) -> Validat
             validate_l3_provenance(manifest, sources)
         }
         ContentType::L3 => validate_l3_provenance(manifest, sources),
+        ContentType::Collection => {
+            validate_derived_sources(manifest, sources)?;
+            validate_derived_depth(manifest, sources)
+        }
         // Handle future content types - for now treat unknown types as invalid
         _ => Err(ValidationError::Internal(
             "unknown content type".to_string(),
@@ -93,6 +103,19 @@ fn validate_l0_provenance(manifest: &Manifest) -> ValidationResult<()> {
 
 /// Validate L3 provenance (derived content).
 fn validate_l3_provenance(manifest: &Manifest, sources: &[Manifest]) -> ValidationResult<()> {
+    validate_derived_sources(manifest, sources)?;
+
+    // Verify root_l0l1 computation
+    let computed_roots = compute_root_entries(sources);
+    if !roots_match(&manifest.provenance.root_l0l1, &computed_roots) {
+        return Err(ValidationError::RootEntriesMismatch);
+    }
+
+    validate_derived_depth(manifest, sources)
+}
+
+/// Check the roots, `derived_from` and self-references of derived content.
+fn validate_derived_sources(manifest: &Manifest, sources: &[Manifest]) -> ValidationResult<()> {
     let prov = &manifest.provenance;
 
     // Must have at least one root
@@ -125,23 +148,21 @@ fn validate_l3_provenance(manifest: &Manifest, sources: &[Manifest]) -> Validati
         }
     }
 
-    // Verify root_l0l1 computation
-    let computed_roots = compute_root_entries(sources);
-    if !roots_match(&prov.root_l0l1, &computed_roots) {
-        return Err(ValidationError::RootEntriesMismatch);
-    }
+    Ok(())
+}
 
-    // Verify depth
+/// Check that the depth of derived content is `max(sources.depth) + 1`.
+fn validate_derived_depth(manifest: &Manifest, sources: &[Manifest]) -> ValidationResult<()> {
     let expected_depth = sources
         .iter()
         .map(|s| s.provenance.depth)
         .max()
         .unwrap_or(0)
         + 1;
-    if prov.depth != expected_depth {
+    if manifest.provenance.depth != expected_depth {
         return Err(ValidationError::DepthMismatch {
             expected: expected_depth,
-            actual: prov.depth,
+            actual: manifest.provenance.depth,
         });
     }
 
@@ -155,23 +176,35 @@ fn validate_l3_provenance(manifest: &Manifest, sources: &[Manifest]) -> Validati
 /// - For L0 sources, adds an additional entry for the source itself
 /// - Merges duplicates by accumulating weights
 fn compute_root_entries(sources: &[Manifest]) -> Vec<ProvenanceEntry> {
+    let weighted: Vec<_> = sources.iter().map(|s| (s, 1)).collect();
+    compute_weighted_root_entries(&weighted)
+}
+
+/// Compute expected root entries from weighted source manifests.
+///
+/// This mirrors `Provenance::from_weighted_sources`: every entry a source
+/// contributes has its weight multiplied by the source's weight.
+pub(crate) fn compute_weighted_root_entries(sources: &[(&Manifest, u32)]) -> Vec<ProvenanceEntry> {
     use std::collections::HashMap;
 
     let mut all_entries = Vec::new();
 
-    for source in sources {
+    for (source, weight) in sources {
         // Collect all root entries from the source
         for entry in &source.provenance.root_l0l1 {
-            all_entries.push(entry.clone());
+            let mut entry = entry.clone();
+            entry.weight = entry.weight.saturating_mul(*weight);
+            all_entries.push(entry);
         }
 
         // If the source is L0, add it as a root entry too
         // (This matches Provenance::from_sources behavior)
         if source.provenance.is_l0() {
-            all_entries.push(ProvenanceEntry::new(
+            all_entries.push(ProvenanceEntry::with_weight(
                 source.hash,
                 source.owner,
                 source.visibility,
+                *weight,
             ));
         }
     }
@@ -191,7 +224,7 @@ fn compute_root_entries(sources: &[Manifest]) -> Vec<ProvenanceEntry> {
 /// Check if two sets of provenance entries match (ignoring order).
 ///
 /// Entries match if they have the same hashes with the same weights.
-fn roots_match(actual: &[ProvenanceEntry], expected: &[ProvenanceEntry]) -> bool {
+pub(crate) fn roots_match(actual: &[ProvenanceEntry], expected: &[ProvenanceEntry]) -> bool {
     use std::collections::HashMap;
 
     if actual.len() != expected.len() {
@@ -320,6 +353,7 @@ mod tests {
             root_l0l1: vec![],
             derived_from: vec![content_hash(b"source")],
             depth: 1,
+            attested: false,
         };
 
         let result = validate_provenance(&l3_manifest, &[]);
@@ -342,6 +376,7 @@ mod tests {
             )],
             derived_from: vec![],
             depth: 1,
+            attested: false,
         };
 
         let result = validate_provenance(&l3_manifest, &[]);
@@ -361,6 +396,7 @@ mod tests {
             root_l0l1: source.provenance.root_l0l1.clone(),
             derived_from: vec![source.hash, l3_hash], // Self-reference!
             depth: 1,
+            attested: false,
         };
 
         let result = validate_provenance(&l3_manifest, &[source]);
@@ -383,6 +419,7 @@ mod tests {
             ],
             derived_from: vec![source.hash],
             depth: 1,
+            attested: false,
         };
 
         let result = validate_provenance(&l3_manifest, &[source]);
@@ -402,6 +439,7 @@ mod tests {
             root_l0l1: source.provenance.root_l0l1.clone(),
             derived_from: vec![source.hash, content_hash(b"unknown")], // Unknown source
             depth: 1,
+            attested: false,
         };
 
         let result = validate_provenance(&l3_manifest, &[source]);
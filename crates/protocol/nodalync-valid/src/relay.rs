@@ -0,0 +1,184 @@
+//! Relay payment validation.
+//!
+//! A query relayed through intermediate peers pays each relay through the
+//! payment channel with the previous hop. The payment's query hash is the
+//! hash of the sealed layer it came with, so a payment can't be moved to
+//! another relayed query. The relay checks the payment covers its fee plus
+//! whatever it must forward to the next hop.
+
+use nodalync_crypto::{Hash, PublicKey};
+use nodalync_types::{Amount, Channel, ChannelState, Payment, PeerId};
+
+use crate::error::{ValidationError, ValidationResult};
+use crate::payment::{validate_payment_nonce, verify_payment_signature};
+
+/// Validate a channel payment for relaying a query, as the relay.
+///
+/// Checks:
+/// 1. `payment.amount >= amount`
+/// 2. `payment.recipient == relay`
+/// 3. `payment.query_hash == layer_hash`
+/// 4. The payment is for this channel, which is open
+/// 5. The payer's channel balance covers the payment
+/// 6. The nonce strictly increases (see [`validate_payment_nonce`])
+/// 7. The payment signature verifies (if the payer's key is known)
+#[allow(clippy::too_many_arguments)]
+pub fn validate_relay_payment(
+    payment: &Payment,
+    amount: Amount,
+    relay: &PeerId,
+    layer_hash: &Hash,
+    channel: &Channel,
+    payer_pubkey: Option<&PublicKey>,
+    payment_nonce: u64,
+    last_nonce: Option<u64>,
+) -> ValidationResult<()> {
+    if payment.amount < amount {
+        return Err(ValidationError::InsufficientPayment {
+            amount: payment.amount,
+            price: amount,
+        });
+    }
+
+    if payment.recipient != *relay {
+        return Err(ValidationError::WrongRecipient {
+            payment_recipient: format!("{}", payment.recipient),
+            owner: format!("{}", relay),
+        });
+    }
+
+    if payment.query_hash != *layer_hash {
+        return Err(ValidationError::QueryHashMismatch);
+    }
+
+    if payment.channel_id != channel.channel_id {
+        return Err(ValidationError::InvalidRelayPayment {
+            reason: "payment is for a different channel".to_string(),
+        });
+    }
+
+    if channel.state != ChannelState::Open {
+        return Err(ValidationError::ChannelNotOpen {
+            state: format!("{:?}", channel.state),
+        });
+    }
+
+    if channel.their_balance < payment.amount {
+        return Err(ValidationError::InsufficientChannelBalance {
+            balance: channel.their_balance,
+            amount: payment.amount,
+        });
+    }
+
+    validate_payment_nonce(payment_nonce, channel.nonce, last_nonce)?;
+
+    if let Some(pubkey) = payer_pubkey {
+        if !verify_payment_signature(pubkey, payment) {
+            return Err(ValidationError::InvalidPaymentSignature);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payment::construct_payment_message;
+    use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key, sign};
+    use nodalync_crypto::{PrivateKey, Signature};
+
+    fn create_relay_payment(
+        channel: &Channel,
+        relay: PeerId,
+        layer_hash: Hash,
+        amount: Amount,
+        payer_key: &PrivateKey,
+    ) -> Payment {
+        let mut payment = Payment::new(
+            content_hash(b"payment"),
+            channel.channel_id,
+            amount,
+            relay,
+            layer_hash,
+            vec![],
+            1_500,
+            Signature::from_bytes([0u8; 64]),
+        );
+        payment.signature = sign(payer_key, &construct_payment_message(&payment));
+        payment
+    }
+
+    #[test]
+    fn test_validate_relay_payment() {
+        let (payer_key, payer_pubkey) = generate_identity();
+        let (_, relay_pubkey) = generate_identity();
+        let relay = peer_id_from_public_key(&relay_pubkey);
+        let layer_hash = content_hash(b"layer");
+        let mut channel = Channel::new(
+            content_hash(b"channel"),
+            peer_id_from_public_key(&payer_pubkey),
+            1_000,
+            1_000,
+        );
+        channel.mark_open(1_000, 1_000);
+
+        let payment = create_relay_payment(&channel, relay, layer_hash, 30, &payer_key);
+        assert!(validate_relay_payment(
+            &payment,
+            30,
+            &relay,
+            &layer_hash,
+            &channel,
+            Some(&payer_pubkey),
+            1,
+            None
+        )
+        .is_ok());
+
+        // Doesn't cover the fee and what must be forwarded
+        assert!(matches!(
+            validate_relay_payment(&payment, 31, &relay, &layer_hash, &channel, None, 1, None),
+            Err(ValidationError::InsufficientPayment { .. })
+        ));
+
+        // Paid for another layer
+        let other = content_hash(b"other layer");
+        assert_eq!(
+            validate_relay_payment(&payment, 30, &relay, &other, &channel, None, 1, None),
+            Err(ValidationError::QueryHashMismatch)
+        );
+
+        // Replayed nonce
+        assert!(matches!(
+            validate_relay_payment(
+                &payment,
+                30,
+                &relay,
+                &layer_hash,
+                &channel,
+                None,
+                1,
+                Some(1)
+            ),
+            Err(ValidationError::ReplayedNonce { .. })
+        ));
+
+        // Signed by someone else
+        let (other_key, _) = generate_identity();
+        let forged = create_relay_payment(&channel, relay, layer_hash, 30, &other_key);
+        assert_eq!(
+            validate_relay_payment(
+                &forged,
+                30,
+                &relay,
+                &layer_hash,
+                &channel,
+                Some(&payer_pubkey),
+                1,
+                None
+            ),
+            Err(ValidationError::InvalidPaymentSignature)
+        );
+    }
+}
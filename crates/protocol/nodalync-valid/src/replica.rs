@@ -0,0 +1,230 @@
+//! Replica delegation and catalog validation.
+//!
+//! A delegation lets a replica announce and serve a primary's content, so
+//! it must only be honoured from the primary and only for the replica it
+//! names. A catalog moves the primary's manifests and content to the
+//! replica; it is signed by the primary, and every item must be owned by
+//! the primary and match its manifest hash.
+
+use nodalync_crypto::{peer_id_from_public_key, sign, verify, PrivateKey, Signature, Timestamp};
+use nodalync_types::ReplicaDelegation;
+use nodalync_wire::{encode_payload, ReplicaCatalog};
+
+use crate::content::validate_content;
+use crate::error::{ValidationError, ValidationResult};
+
+/// Sign a replica delegation as the primary.
+///
+/// Recomputes the hash from the terms before signing it.
+pub fn sign_replica_delegation(private_key: &PrivateKey, delegation: &mut ReplicaDelegation) {
+    delegation.hash = delegation.compute_hash();
+    delegation.signature = sign(private_key, &delegation.hash.0);
+}
+
+/// Validate a replica delegation and its primary signature.
+///
+/// Checks:
+/// 1. `hash` matches the terms
+/// 2. `primary` is derived from `primary_key`
+/// 3. `primary` and `replica` differ
+/// 4. `expires_at` is after `issued_at`, and `now` is before `expires_at`
+/// 5. The signature over `hash` verifies against `primary_key`
+pub fn validate_replica_delegation(
+    delegation: &ReplicaDelegation,
+    now: Timestamp,
+) -> ValidationResult<()> {
+    if delegation.hash != delegation.compute_hash() {
+        return Err(invalid_delegation(
+            "hash does not match the delegation terms",
+        ));
+    }
+
+    if delegation.primary != peer_id_from_public_key(&delegation.primary_key) {
+        return Err(invalid_delegation("primary does not match primary key"));
+    }
+
+    if delegation.primary == delegation.replica {
+        return Err(invalid_delegation("primary cannot delegate to itself"));
+    }
+
+    if delegation.expires_at <= delegation.issued_at {
+        return Err(invalid_delegation("expires before it was issued"));
+    }
+    if delegation.is_expired(now) {
+        return Err(invalid_delegation("expired"));
+    }
+
+    if !verify(
+        &delegation.primary_key,
+        &delegation.hash.0,
+        &delegation.signature,
+    ) {
+        return Err(invalid_delegation("signature does not verify"));
+    }
+
+    Ok(())
+}
+
+/// Construct the message bytes for catalog signing/verification.
+///
+/// The message is the CBOR encoding of the catalog with a zeroed
+/// signature.
+pub fn construct_replica_catalog_message(catalog: &ReplicaCatalog) -> ValidationResult<Vec<u8>> {
+    let unsigned = ReplicaCatalog {
+        signature: Signature::from_bytes([0u8; 64]),
+        ..catalog.clone()
+    };
+    encode_payload(&unsigned).map_err(|e| invalid_catalog(format!("encoding failed: {}", e)))
+}
+
+/// Sign a replica catalog as the primary.
+pub fn sign_replica_catalog(
+    private_key: &PrivateKey,
+    catalog: &mut ReplicaCatalog,
+) -> ValidationResult<()> {
+    let message = construct_replica_catalog_message(catalog)?;
+    catalog.signature = sign(private_key, &message);
+    Ok(())
+}
+
+/// Validate a replica catalog.
+///
+/// Checks:
+/// 1. The delegation is valid at `now`
+/// 2. The signature verifies against the delegation's `primary_key`
+/// 3. Every manifest is owned by the primary
+/// 4. Every item's content matches its manifest
+pub fn validate_replica_catalog(catalog: &ReplicaCatalog, now: Timestamp) -> ValidationResult<()> {
+    let delegation = &catalog.delegation;
+    validate_replica_delegation(delegation, now)?;
+
+    let message = construct_replica_catalog_message(catalog)?;
+    if !verify(&delegation.primary_key, &message, &catalog.signature) {
+        return Err(invalid_catalog("signature does not verify"));
+    }
+
+    for item in &catalog.items {
+        if item.manifest.owner != delegation.primary {
+            return Err(invalid_catalog(format!(
+                "{} is not owned by the primary",
+                item.manifest.hash
+            )));
+        }
+        validate_content(&item.content, &item.manifest)
+            .map_err(|e| invalid_catalog(format!("{}: {}", item.manifest.hash, e)))?;
+    }
+
+    Ok(())
+}
+
+fn invalid_delegation(reason: impl Into<String>) -> ValidationError {
+    ValidationError::InvalidReplicaDelegation {
+        reason: reason.into(),
+    }
+}
+
+fn invalid_catalog(reason: impl Into<String>) -> ValidationError {
+    ValidationError::InvalidReplicaCatalog {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, generate_identity};
+    use nodalync_types::{Manifest, Metadata, PeerId};
+    use nodalync_wire::ReplicaItem;
+
+    fn signed_delegation() -> (ReplicaDelegation, PrivateKey) {
+        let (private_key, public_key) = generate_identity();
+        let mut delegation = ReplicaDelegation::new(
+            peer_id_from_public_key(&public_key),
+            public_key,
+            PeerId::from_bytes([2u8; 20]),
+            Some("0.0.12345".to_string()),
+            1_000,
+            2_000,
+        );
+        sign_replica_delegation(&private_key, &mut delegation);
+        (delegation, private_key)
+    }
+
+    fn signed_catalog() -> (ReplicaCatalog, PrivateKey) {
+        let (delegation, private_key) = signed_delegation();
+        let content = b"replicated".to_vec();
+        let manifest = Manifest::new_l0(
+            content_hash(&content),
+            delegation.primary,
+            Metadata::new("Replicated", content.len() as u64),
+            1_000,
+        );
+        let mut catalog = ReplicaCatalog {
+            delegation,
+            created_at: 1_000,
+            items: vec![ReplicaItem { manifest, content }],
+            signature: Signature::from_bytes([0u8; 64]),
+        };
+        sign_replica_catalog(&private_key, &mut catalog).unwrap();
+        (catalog, private_key)
+    }
+
+    #[test]
+    fn test_valid_delegation() {
+        let (delegation, _) = signed_delegation();
+        assert!(validate_replica_delegation(&delegation, 1_500).is_ok());
+        assert!(matches!(
+            validate_replica_delegation(&delegation, 2_000),
+            Err(ValidationError::InvalidReplicaDelegation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_tampered_delegation_rejected() {
+        let (delegation, private_key) = signed_delegation();
+
+        let mut tampered = delegation.clone();
+        tampered.replica = PeerId::from_bytes([3u8; 20]);
+        assert!(validate_replica_delegation(&tampered, 1_500).is_err());
+
+        // Re-hashed but not re-signed
+        tampered.hash = tampered.compute_hash();
+        assert!(validate_replica_delegation(&tampered, 1_500).is_err());
+
+        // A primary can't delegate to itself
+        let mut to_self = delegation;
+        to_self.replica = to_self.primary;
+        sign_replica_delegation(&private_key, &mut to_self);
+        assert!(validate_replica_delegation(&to_self, 1_500).is_err());
+    }
+
+    #[test]
+    fn test_valid_catalog() {
+        let (catalog, _) = signed_catalog();
+        assert!(validate_replica_catalog(&catalog, 1_500).is_ok());
+    }
+
+    #[test]
+    fn test_tampered_catalog_rejected() {
+        let (catalog, private_key) = signed_catalog();
+
+        let mut tampered = catalog.clone();
+        tampered.items[0].manifest.economics.price = 1;
+        assert!(matches!(
+            validate_replica_catalog(&tampered, 1_500),
+            Err(ValidationError::InvalidReplicaCatalog { .. })
+        ));
+
+        // Content that doesn't match its manifest, even when signed
+        let mut swapped = catalog.clone();
+        swapped.items[0].content = b"something else".to_vec();
+        sign_replica_catalog(&private_key, &mut swapped).unwrap();
+        assert!(validate_replica_catalog(&swapped, 1_500).is_err());
+
+        // Manifests owned by someone else, even when signed
+        let mut foreign = catalog;
+        foreign.items[0].manifest.owner = PeerId::from_bytes([9u8; 20]);
+        sign_replica_catalog(&private_key, &mut foreign).unwrap();
+        assert!(validate_replica_catalog(&foreign, 1_500).is_err());
+    }
+}
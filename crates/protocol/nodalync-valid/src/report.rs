@@ -0,0 +1,119 @@
+//! Content report validation.
+//!
+//! Reports feed each node's moderation policy, which may hide content once
+//! enough reputable peers report it. A report must therefore be signed by
+//! the peer it names, so reports can't be forged in someone else's name
+//! and each reporter counts once. Validation checks the hash, that the key
+//! belongs to the reporter, the comment length, and the signature.
+
+use nodalync_crypto::{peer_id_from_public_key, sign, verify, PrivateKey};
+use nodalync_types::{ContentReport, MAX_REPORT_COMMENT_LENGTH};
+
+use crate::error::{ValidationError, ValidationResult};
+
+/// Sign a content report as the reporter.
+///
+/// Recomputes the hash from the terms before signing it.
+pub fn sign_report(private_key: &PrivateKey, report: &mut ContentReport) {
+    report.hash = report.compute_hash();
+    report.signature = sign(private_key, &report.hash.0);
+}
+
+/// Validate a content report and its reporter signature.
+///
+/// Checks:
+/// 1. `hash` matches the terms
+/// 2. `reporter` is derived from `reporter_key`
+/// 3. `comment` is at most [`MAX_REPORT_COMMENT_LENGTH`] characters
+/// 4. The signature over `hash` verifies against `reporter_key`
+pub fn validate_report(report: &ContentReport) -> ValidationResult<()> {
+    if report.hash != report.compute_hash() {
+        return Err(invalid("hash does not match the report terms"));
+    }
+
+    if report.reporter != peer_id_from_public_key(&report.reporter_key) {
+        return Err(invalid("reporter does not match reporter key"));
+    }
+
+    if report.comment.chars().count() > MAX_REPORT_COMMENT_LENGTH {
+        return Err(invalid(format!(
+            "comment exceeds {} characters",
+            MAX_REPORT_COMMENT_LENGTH
+        )));
+    }
+
+    if !verify(&report.reporter_key, &report.hash.0, &report.signature) {
+        return Err(ValidationError::InvalidReportSignature);
+    }
+
+    Ok(())
+}
+
+fn invalid(reason: impl Into<String>) -> ValidationError {
+    ValidationError::InvalidReport {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, generate_identity};
+    use nodalync_types::ReportReason;
+
+    fn create_signed_report(comment: &str) -> ContentReport {
+        let (private_key, public_key) = generate_identity();
+        let mut report = ContentReport::new(
+            content_hash(b"reported"),
+            peer_id_from_public_key(&public_key),
+            public_key,
+            ReportReason::Spam,
+            comment,
+            1_000,
+        );
+        sign_report(&private_key, &mut report);
+        report
+    }
+
+    #[test]
+    fn test_valid_report() {
+        assert!(validate_report(&create_signed_report("")).is_ok());
+        assert!(validate_report(&create_signed_report("Link farm")).is_ok());
+    }
+
+    #[test]
+    fn test_report_comment_too_long() {
+        let comment = "x".repeat(MAX_REPORT_COMMENT_LENGTH + 1);
+        assert!(matches!(
+            validate_report(&create_signed_report(&comment)),
+            Err(ValidationError::InvalidReport { .. })
+        ));
+    }
+
+    #[test]
+    fn test_report_tampering() {
+        let report = create_signed_report("Link farm");
+
+        // Changing the reason without re-hashing
+        let mut changed = report.clone();
+        changed.reason = ReportReason::Illegal;
+        assert!(validate_report(&changed).is_err());
+
+        // Re-hashed but not re-signed
+        changed.hash = changed.compute_hash();
+        assert!(matches!(
+            validate_report(&changed),
+            Err(ValidationError::InvalidReportSignature)
+        ));
+
+        // Signed with another key in someone else's name
+        let (other_key, other_public) = generate_identity();
+        let mut forged = report.clone();
+        forged.reporter_key = other_public;
+        sign_report(&other_key, &mut forged);
+        assert!(matches!(
+            validate_report(&forged),
+            Err(ValidationError::InvalidReport { .. })
+        ));
+    }
+}
@@ -0,0 +1,164 @@
+//! Revocation certificate validation and revoked-key checks.
+//!
+//! A revocation certificate is signed by the key it revokes, so it is
+//! self-authenticating: validation checks the hash, that the key belongs
+//! to the revoked peer, and the signature. Once a key is revoked, messages
+//! it sent and manifests it owns are rejected; the checks resolve revoked
+//! peers through a [`RevocationList`].
+//!
+//! A leaked key can sign anything with any timestamp, so nothing from a
+//! revoked key is accepted, however old it claims to be.
+
+use std::collections::HashSet;
+
+use nodalync_crypto::{peer_id_from_public_key, sign, verify, PrivateKey};
+use nodalync_types::{Manifest, PeerId, RevocationCertificate};
+use nodalync_wire::Message;
+
+use crate::error::{ValidationError, ValidationResult};
+
+/// Callback trait for looking up revoked keys.
+pub trait RevocationList {
+    /// Check if a peer's identity key has been revoked.
+    fn is_revoked(&self, peer: &PeerId) -> bool;
+}
+
+/// Revoked peers held in memory.
+impl RevocationList for HashSet<PeerId> {
+    fn is_revoked(&self, peer: &PeerId) -> bool {
+        self.contains(peer)
+    }
+}
+
+/// Sign a revocation certificate with the key it revokes.
+///
+/// Recomputes the hash from the terms before signing it.
+pub fn sign_revocation(private_key: &PrivateKey, certificate: &mut RevocationCertificate) {
+    certificate.hash = certificate.compute_hash();
+    certificate.signature = sign(private_key, &certificate.hash.0);
+}
+
+/// Validate a revocation certificate and its signature.
+///
+/// Checks:
+/// 1. `hash` matches the terms
+/// 2. `peer_id` is derived from `public_key`
+/// 3. The signature over `hash` verifies against `public_key`
+pub fn validate_revocation(certificate: &RevocationCertificate) -> ValidationResult<()> {
+    if certificate.hash != certificate.compute_hash() {
+        return Err(invalid("hash does not match the certificate terms"));
+    }
+
+    if certificate.peer_id != peer_id_from_public_key(&certificate.public_key) {
+        return Err(invalid("peer ID does not match public key"));
+    }
+
+    if !verify(
+        &certificate.public_key,
+        &certificate.hash.0,
+        &certificate.signature,
+    ) {
+        return Err(ValidationError::InvalidRevocationSignature);
+    }
+
+    Ok(())
+}
+
+/// Reject a peer whose key has been revoked.
+pub fn validate_not_revoked(peer: &PeerId, revoked: &dyn RevocationList) -> ValidationResult<()> {
+    if revoked.is_revoked(peer) {
+        return Err(ValidationError::KeyRevoked { peer_id: *peer });
+    }
+    Ok(())
+}
+
+/// Reject a message sent by a revoked key.
+pub fn validate_message_not_revoked(
+    message: &Message,
+    revoked: &dyn RevocationList,
+) -> ValidationResult<()> {
+    validate_not_revoked(&message.sender, revoked)
+}
+
+/// Reject a manifest owned by a revoked key.
+pub fn validate_manifest_not_revoked(
+    manifest: &Manifest,
+    revoked: &dyn RevocationList,
+) -> ValidationResult<()> {
+    validate_not_revoked(&manifest.owner, revoked)
+}
+
+fn invalid(reason: impl Into<String>) -> ValidationError {
+    ValidationError::InvalidRevocation {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, generate_identity};
+    use nodalync_types::Metadata;
+
+    fn create_signed_revocation() -> (RevocationCertificate, PrivateKey) {
+        let (private_key, public_key) = generate_identity();
+        let mut certificate =
+            RevocationCertificate::new(peer_id_from_public_key(&public_key), public_key, 1_000);
+        sign_revocation(&private_key, &mut certificate);
+        (certificate, private_key)
+    }
+
+    #[test]
+    fn test_valid_revocation() {
+        let (certificate, _) = create_signed_revocation();
+        assert!(validate_revocation(&certificate).is_ok());
+    }
+
+    #[test]
+    fn test_revocation_tampering() {
+        let (certificate, _) = create_signed_revocation();
+
+        // Retargeting without re-hashing
+        let mut changed = certificate.clone();
+        changed.created_at += 1;
+        assert!(validate_revocation(&changed).is_err());
+
+        // Re-hashed but not re-signed
+        changed.hash = changed.compute_hash();
+        assert!(matches!(
+            validate_revocation(&changed),
+            Err(ValidationError::InvalidRevocationSignature)
+        ));
+
+        // Revoking someone else's identity with our own key
+        let (other_key, other_public) = generate_identity();
+        let mut forged = certificate.clone();
+        forged.public_key = other_public;
+        sign_revocation(&other_key, &mut forged);
+        assert!(matches!(
+            validate_revocation(&forged),
+            Err(ValidationError::InvalidRevocation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_revoked_peers_rejected() {
+        let (certificate, _) = create_signed_revocation();
+        let revoked: HashSet<PeerId> = [certificate.peer_id].into_iter().collect();
+
+        let manifest = Manifest::new_l0(
+            content_hash(b"signed with a leaked key"),
+            certificate.peer_id,
+            Metadata::new("Leaked", 24),
+            2_000,
+        );
+        assert!(matches!(
+            validate_manifest_not_revoked(&manifest, &revoked),
+            Err(ValidationError::KeyRevoked { peer_id }) if peer_id == certificate.peer_id
+        ));
+
+        let (_, public_key) = generate_identity();
+        let other = peer_id_from_public_key(&public_key);
+        assert!(validate_not_revoked(&other, &revoked).is_ok());
+    }
+}
@@ -0,0 +1,697 @@
+//! Structured metadata validation.
+//!
+//! Metadata may carry structured `fields` (a JSON object) along with the URI
+//! of the schema they conform to, so that consumers such as marketplaces can
+//! rely on well-formed fields. Schemas use a subset of JSON Schema:
+//!
+//! - `type` (a type name or a list of names)
+//! - `properties`, `required`, `additionalProperties` (boolean or schema)
+//! - `items`, `minItems`, `maxItems`, `uniqueItems`
+//! - `enum`, `const`
+//! - `minLength`, `maxLength`, `pattern`
+//! - `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum`
+//!
+//! Annotations (`title`, `description`, `format`, ...) are ignored. Any
+//! other keyword makes the schema invalid, so a schema is never silently
+//! half-enforced.
+
+use nodalync_types::Metadata;
+use regex::Regex;
+use serde_json::{Map, Value};
+
+use crate::error::{ValidationError, ValidationResult};
+
+/// URI of the built-in dataset schema.
+pub const DATASET_SCHEMA_URI: &str = "nodalync:schema/dataset/v1";
+
+/// URI of the built-in citation schema.
+pub const CITATION_SCHEMA_URI: &str = "nodalync:schema/citation/v1";
+
+/// URI of the built-in IPFS schema.
+pub const IPFS_SCHEMA_URI: &str = "nodalync:schema/ipfs/v1";
+
+/// Built-in dataset schema: format, size and columns of tabular data.
+const DATASET_SCHEMA: &str = r#"{
+  "title": "Dataset",
+  "type": "object",
+  "required": ["format"],
+  "properties": {
+    "format": {"enum": ["csv", "tsv", "json", "jsonl", "parquet", "sqlite", "other"]},
+    "rows": {"type": "integer", "minimum": 0},
+    "columns": {
+      "type": "array",
+      "items": {
+        "type": "object",
+        "required": ["name"],
+        "properties": {
+          "name": {"type": "string", "minLength": 1, "maxLength": 200},
+          "type": {"type": "string", "maxLength": 50},
+          "description": {"type": "string", "maxLength": 2000}
+        },
+        "additionalProperties": false
+      }
+    },
+    "license": {"type": "string", "maxLength": 100},
+    "source": {"type": "string", "maxLength": 2000}
+  }
+}"#;
+
+/// Built-in citation schema: bibliographic fields of a paper.
+const CITATION_SCHEMA: &str = r#"{
+  "title": "Citation",
+  "type": "object",
+  "required": ["authors", "year"],
+  "properties": {
+    "authors": {
+      "type": "array",
+      "minItems": 1,
+      "items": {"type": "string", "minLength": 1, "maxLength": 200}
+    },
+    "year": {"type": "integer", "minimum": 1000, "maximum": 9999},
+    "venue": {"type": "string", "maxLength": 500},
+    "volume": {"type": "string", "maxLength": 50},
+    "pages": {"type": "string", "maxLength": 50},
+    "doi": {"type": "string", "pattern": "^10\\.[0-9]{4,9}/\\S+$"},
+    "arxiv": {"type": "string", "pattern": "^[0-9]{4}\\.[0-9]{4,5}(v[0-9]+)?$"},
+    "url": {"type": "string", "pattern": "^https?://"}
+  }
+}"#;
+
+/// Built-in IPFS schema: the CID content was imported from.
+const IPFS_SCHEMA: &str = r#"{
+  "title": "IPFS import",
+  "type": "object",
+  "required": ["cid"],
+  "properties": {
+    "cid": {"type": "string", "pattern": "^(Qm[1-9A-HJ-NP-Za-km-z]{44}|b[a-z2-7]{8,})$"},
+    "source": {"type": "string", "maxLength": 2000},
+    "verified": {"type": "boolean"},
+    "pin": {"type": "string", "pattern": "^(Qm[1-9A-HJ-NP-Za-km-z]{44}|b[a-z2-7]{8,})$"}
+  },
+  "additionalProperties": false
+}"#;
+
+/// Keywords that constrain values.
+const SUPPORTED_KEYWORDS: &[&str] = &[
+    "type",
+    "properties",
+    "required",
+    "additionalProperties",
+    "items",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+    "enum",
+    "const",
+    "minLength",
+    "maxLength",
+    "pattern",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+];
+
+/// Keywords that only describe values and are ignored.
+const ANNOTATION_KEYWORDS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "format",
+    "default",
+    "examples",
+    "deprecated",
+];
+
+/// JSON types accepted by the `type` keyword.
+const TYPE_NAMES: &[&str] = &[
+    "object", "array", "string", "number", "integer", "boolean", "null",
+];
+
+/// Get a built-in schema by URI.
+pub fn builtin_schema(uri: &str) -> Option<&'static str> {
+    match uri {
+        DATASET_SCHEMA_URI => Some(DATASET_SCHEMA),
+        CITATION_SCHEMA_URI => Some(CITATION_SCHEMA),
+        IPFS_SCHEMA_URI => Some(IPFS_SCHEMA),
+        _ => None,
+    }
+}
+
+/// Check that a schema is well-formed and only uses supported keywords.
+///
+/// The root of a metadata schema must describe an object.
+pub fn validate_schema(schema: &str) -> ValidationResult<()> {
+    let schema = parse_schema(schema)?;
+    check_schema(&schema, "")?;
+    if schema.get("type").and_then(Value::as_str) != Some("object") {
+        return Err(invalid_schema("", "root type must be \"object\""));
+    }
+    Ok(())
+}
+
+/// Validate structured metadata fields against their schema.
+///
+/// `schema` is the schema `metadata.schema` resolves to, or `None` if it
+/// couldn't be resolved. Metadata without fields or a schema URI is valid.
+///
+/// Checks:
+/// 1. Fields are only given with a schema URI
+/// 2. The schema URI resolved to a schema
+/// 3. The schema is valid (see [`validate_schema`])
+/// 4. Fields are a JSON object that conforms to the schema
+pub fn validate_structured_metadata(
+    metadata: &Metadata,
+    schema: Option<&str>,
+) -> ValidationResult<()> {
+    let (uri, fields) = match (&metadata.schema, &metadata.fields) {
+        (None, None) => return Ok(()),
+        (None, Some(_)) => return Err(ValidationError::FieldsWithoutSchema),
+        (Some(uri), fields) => (uri, fields.as_deref().unwrap_or("{}")),
+    };
+
+    let schema = schema.ok_or_else(|| ValidationError::UnknownSchema { uri: uri.clone() })?;
+    validate_schema(schema)?;
+    let schema = parse_schema(schema)?;
+
+    let fields: Value =
+        serde_json::from_str(fields).map_err(|e| ValidationError::InvalidFields {
+            path: "/".to_string(),
+            reason: format!("not valid JSON: {}", e),
+        })?;
+    validate_value(&schema, &fields, "")
+}
+
+/// Parse a schema document.
+fn parse_schema(schema: &str) -> ValidationResult<Value> {
+    serde_json::from_str(schema).map_err(|e| invalid_schema("", &format!("not valid JSON: {}", e)))
+}
+
+/// Check a (sub)schema and its children.
+fn check_schema(schema: &Value, path: &str) -> ValidationResult<()> {
+    let schema = schema
+        .as_object()
+        .ok_or_else(|| invalid_schema(path, "schema must be an object"))?;
+
+    for (keyword, value) in schema {
+        if ANNOTATION_KEYWORDS.contains(&keyword.as_str()) {
+            continue;
+        }
+        if !SUPPORTED_KEYWORDS.contains(&keyword.as_str()) {
+            return Err(invalid_schema(
+                path,
+                &format!("unsupported keyword \"{}\"", keyword),
+            ));
+        }
+
+        let keyword_path = format!("{}/{}", path, keyword);
+        match keyword.as_str() {
+            "type" => {
+                let names: Vec<&Value> = match value {
+                    Value::Array(names) => names.iter().collect(),
+                    name => vec![name],
+                };
+                for name in names {
+                    if !name.as_str().is_some_and(|n| TYPE_NAMES.contains(&n)) {
+                        return Err(invalid_schema(&keyword_path, "unknown type"));
+                    }
+                }
+            }
+            "properties" => {
+                let properties = value
+                    .as_object()
+                    .ok_or_else(|| invalid_schema(&keyword_path, "must be an object"))?;
+                for (name, property) in properties {
+                    check_schema(property, &format!("{}/{}", keyword_path, name))?;
+                }
+            }
+            "required" => {
+                let all_strings = value
+                    .as_array()
+                    .is_some_and(|names| names.iter().all(Value::is_string));
+                if !all_strings {
+                    return Err(invalid_schema(&keyword_path, "must be an array of strings"));
+                }
+            }
+            "additionalProperties" => {
+                if !value.is_boolean() {
+                    check_schema(value, &keyword_path)?;
+                }
+            }
+            "items" => check_schema(value, &keyword_path)?,
+            "uniqueItems" => {
+                if !value.is_boolean() {
+                    return Err(invalid_schema(&keyword_path, "must be a boolean"));
+                }
+            }
+            "enum" => {
+                if value.as_array().is_none_or(|values| values.is_empty()) {
+                    return Err(invalid_schema(&keyword_path, "must be a non-empty array"));
+                }
+            }
+            "const" => {}
+            "minItems" | "maxItems" | "minLength" | "maxLength" => {
+                if !value.is_u64() {
+                    return Err(invalid_schema(
+                        &keyword_path,
+                        "must be a non-negative integer",
+                    ));
+                }
+            }
+            "pattern" => {
+                let pattern = value
+                    .as_str()
+                    .ok_or_else(|| invalid_schema(&keyword_path, "must be a string"))?;
+                Regex::new(pattern).map_err(|e| invalid_schema(&keyword_path, &e.to_string()))?;
+            }
+            "minimum" | "maximum" | "exclusiveMinimum" | "exclusiveMaximum" => {
+                if !value.is_number() {
+                    return Err(invalid_schema(&keyword_path, "must be a number"));
+                }
+            }
+            _ => unreachable!("keyword checked against SUPPORTED_KEYWORDS"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate a value against a checked (sub)schema.
+fn validate_value(schema: &Value, value: &Value, path: &str) -> ValidationResult<()> {
+    let Some(schema) = schema.as_object() else {
+        return Ok(());
+    };
+
+    if let Some(types) = schema.get("type") {
+        let matches = match types {
+            Value::Array(names) => names.iter().any(|n| is_type(value, n)),
+            name => is_type(value, name),
+        };
+        if !matches {
+            return Err(invalid_fields(
+                path,
+                format!(
+                    "expected {}, got {}",
+                    types_display(types),
+                    type_name(value)
+                ),
+            ));
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            return Err(invalid_fields(path, "not one of the allowed values"));
+        }
+    }
+
+    if let Some(expected) = schema.get("const") {
+        if value != expected {
+            return Err(invalid_fields(path, format!("must equal {}", expected)));
+        }
+    }
+
+    match value {
+        Value::Object(object) => validate_object(schema, object, path)?,
+        Value::Array(items) => validate_array(schema, items, path)?,
+        Value::String(s) => validate_string(schema, s, path)?,
+        Value::Number(n) => validate_number(schema, n.as_f64().unwrap_or(f64::NAN), path)?,
+        Value::Bool(_) | Value::Null => {}
+    }
+
+    Ok(())
+}
+
+/// Validate object keywords.
+fn validate_object(
+    schema: &Map<String, Value>,
+    object: &Map<String, Value>,
+    path: &str,
+) -> ValidationResult<()> {
+    if let Some(required) = schema.get("required").and_then(Value::as_array) {
+        for name in required.iter().filter_map(Value::as_str) {
+            if !object.contains_key(name) {
+                return Err(invalid_fields(
+                    path,
+                    format!("missing required field \"{}\"", name),
+                ));
+            }
+        }
+    }
+
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for (name, field) in object {
+        let field_path = format!("{}/{}", path, name);
+        match properties.and_then(|p| p.get(name)) {
+            Some(property) => validate_value(property, field, &field_path)?,
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    return Err(invalid_fields(&field_path, "unknown field"));
+                }
+                Some(additional @ Value::Object(_)) => {
+                    validate_value(additional, field, &field_path)?
+                }
+                _ => {}
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate array keywords.
+fn validate_array(
+    schema: &Map<String, Value>,
+    items: &[Value],
+    path: &str,
+) -> ValidationResult<()> {
+    if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+        if (items.len() as u64) < min {
+            return Err(invalid_fields(
+                path,
+                format!("at least {} items required", min),
+            ));
+        }
+    }
+    if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+        if items.len() as u64 > max {
+            return Err(invalid_fields(
+                path,
+                format!("at most {} items allowed", max),
+            ));
+        }
+    }
+    if schema.get("uniqueItems") == Some(&Value::Bool(true)) {
+        for (i, item) in items.iter().enumerate() {
+            if items[..i].contains(item) {
+                return Err(invalid_fields(&format!("{}/{}", path, i), "duplicate item"));
+            }
+        }
+    }
+    if let Some(item_schema) = schema.get("items") {
+        for (i, item) in items.iter().enumerate() {
+            validate_value(item_schema, item, &format!("{}/{}", path, i))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate string keywords.
+fn validate_string(schema: &Map<String, Value>, s: &str, path: &str) -> ValidationResult<()> {
+    let length = s.chars().count() as u64;
+    if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+        if length < min {
+            return Err(invalid_fields(path, format!("shorter than {} chars", min)));
+        }
+    }
+    if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+        if length > max {
+            return Err(invalid_fields(path, format!("longer than {} chars", max)));
+        }
+    }
+    if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+        let regex = Regex::new(pattern).map_err(|e| invalid_schema(path, &e.to_string()))?;
+        if !regex.is_match(s) {
+            return Err(invalid_fields(path, format!("does not match {}", pattern)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate number keywords.
+fn validate_number(schema: &Map<String, Value>, n: f64, path: &str) -> ValidationResult<()> {
+    let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+
+    if let Some(min) = bound("minimum") {
+        if n < min {
+            return Err(invalid_fields(path, format!("less than {}", min)));
+        }
+    }
+    if let Some(max) = bound("maximum") {
+        if n > max {
+            return Err(invalid_fields(path, format!("greater than {}", max)));
+        }
+    }
+    if let Some(min) = bound("exclusiveMinimum") {
+        if n <= min {
+            return Err(invalid_fields(
+                path,
+                format!("must be greater than {}", min),
+            ));
+        }
+    }
+    if let Some(max) = bound("exclusiveMaximum") {
+        if n >= max {
+            return Err(invalid_fields(path, format!("must be less than {}", max)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check a value against a type name.
+fn is_type(value: &Value, name: &Value) -> bool {
+    match name.as_str() {
+        Some("object") => value.is_object(),
+        Some("array") => value.is_array(),
+        Some("string") => value.is_string(),
+        Some("number") => value.is_number(),
+        Some("integer") => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }
+        Some("boolean") => value.is_boolean(),
+        Some("null") => value.is_null(),
+        _ => false,
+    }
+}
+
+/// JSON type name of a value.
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Number(_) => "number",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+    }
+}
+
+/// Display a `type` keyword value.
+fn types_display(types: &Value) -> String {
+    match types {
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        name => name.as_str().unwrap_or_default().to_string(),
+    }
+}
+
+fn invalid_schema(path: &str, reason: &str) -> ValidationError {
+    ValidationError::InvalidSchema {
+        reason: if path.is_empty() {
+            reason.to_string()
+        } else {
+            format!("{}: {}", path, reason)
+        },
+    }
+}
+
+fn invalid_fields(path: &str, reason: impl Into<String>) -> ValidationError {
+    ValidationError::InvalidFields {
+        path: if path.is_empty() {
+            "/".to_string()
+        } else {
+            path.to_string()
+        },
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn citation(fields: &str) -> Metadata {
+        Metadata::new("Paper", 100).with_fields(CITATION_SCHEMA_URI, fields)
+    }
+
+    fn check(metadata: &Metadata) -> ValidationResult<()> {
+        let schema = metadata.schema.as_deref().and_then(builtin_schema);
+        validate_structured_metadata(metadata, schema)
+    }
+
+    fn invalid_path(result: ValidationResult<()>) -> String {
+        match result {
+            Err(ValidationError::InvalidFields { path, .. }) => path,
+            other => panic!("expected InvalidFields, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_builtin_schemas_are_valid() {
+        for uri in [DATASET_SCHEMA_URI, CITATION_SCHEMA_URI, IPFS_SCHEMA_URI] {
+            validate_schema(builtin_schema(uri).unwrap()).unwrap();
+        }
+        assert!(builtin_schema("example:missing").is_none());
+    }
+
+    #[test]
+    fn test_plain_metadata_is_valid() {
+        assert!(check(&Metadata::new("Plain", 1)).is_ok());
+    }
+
+    #[test]
+    fn test_valid_fields() {
+        let metadata = citation(
+            r#"{"authors":["Ada Lovelace"],"year":1843,"doi":"10.1000/xyz123","extra":true}"#,
+        );
+        assert!(check(&metadata).is_ok());
+
+        let dataset = Metadata::new("Data", 100).with_fields(
+            DATASET_SCHEMA_URI,
+            r#"{"format":"csv","rows":10,"columns":[{"name":"id","type":"integer"}]}"#,
+        );
+        assert!(check(&dataset).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_fields() {
+        // Missing required field
+        assert_eq!(invalid_path(check(&citation(r#"{"year":2020}"#))), "/");
+        // Wrong type
+        assert_eq!(
+            invalid_path(check(&citation(r#"{"authors":["A"],"year":"2020"}"#))),
+            "/year"
+        );
+        // Non-integer number
+        assert_eq!(
+            invalid_path(check(&citation(r#"{"authors":["A"],"year":2020.5}"#))),
+            "/year"
+        );
+        // Bound
+        assert_eq!(
+            invalid_path(check(&citation(r#"{"authors":["A"],"year":99}"#))),
+            "/year"
+        );
+        // minItems
+        assert_eq!(
+            invalid_path(check(&citation(r#"{"authors":[],"year":2020}"#))),
+            "/authors"
+        );
+        // Array items
+        assert_eq!(
+            invalid_path(check(&citation(r#"{"authors":["A",""],"year":2020}"#))),
+            "/authors/1"
+        );
+        // Pattern
+        assert_eq!(
+            invalid_path(check(&citation(
+                r#"{"authors":["A"],"year":2020,"doi":"not-a-doi"}"#
+            ))),
+            "/doi"
+        );
+        // Not JSON, or not an object
+        assert!(check(&citation("{")).is_err());
+        assert_eq!(invalid_path(check(&citation("[]"))), "/");
+
+        // Enum and additionalProperties: false in nested objects
+        let dataset =
+            |fields: &str| Metadata::new("Data", 1).with_fields(DATASET_SCHEMA_URI, fields);
+        assert_eq!(
+            invalid_path(check(&dataset(r#"{"format":"xlsx"}"#))),
+            "/format"
+        );
+        assert_eq!(
+            invalid_path(check(&dataset(
+                r#"{"format":"csv","columns":[{"name":"id","unit":"m"}]}"#
+            ))),
+            "/columns/0/unit"
+        );
+    }
+
+    #[test]
+    fn test_schema_resolution() {
+        let metadata = Metadata::new("Paper", 1).with_fields("example:paper", "{}");
+        assert!(matches!(
+            validate_structured_metadata(&metadata, None),
+            Err(ValidationError::UnknownSchema { .. })
+        ));
+
+        let mut no_schema = metadata.clone();
+        no_schema.schema = None;
+        assert!(matches!(
+            validate_structured_metadata(&no_schema, None),
+            Err(ValidationError::FieldsWithoutSchema)
+        ));
+
+        // A schema URI without fields is checked against an empty object
+        let schema = r#"{"type":"object","required":["doi"]}"#;
+        let mut no_fields = metadata;
+        no_fields.fields = None;
+        assert!(validate_structured_metadata(&no_fields, Some(schema)).is_err());
+        assert!(validate_structured_metadata(&no_fields, Some(r#"{"type":"object"}"#)).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_schemas() {
+        let invalid = [
+            "not json",
+            r#"{"type":"array"}"#,
+            r##"{"type":"object","properties":{"a":{"$ref":"#/defs/a"}}}"##,
+            r#"{"type":"object","anyOf":[]}"#,
+            r#"{"type":"object","properties":{"a":{"type":"float"}}}"#,
+            r#"{"type":"object","required":"a"}"#,
+            r#"{"type":"object","properties":{"a":{"pattern":"("}}}"#,
+            r#"{"type":"object","properties":{"a":{"minLength":-1}}}"#,
+            r#"{"type":"object","properties":{"a":{"enum":[]}}}"#,
+        ];
+        for schema in invalid {
+            assert!(
+                matches!(
+                    validate_schema(schema),
+                    Err(ValidationError::InvalidSchema { .. })
+                ),
+                "{} should be rejected",
+                schema
+            );
+        }
+
+        // Annotations are ignored
+        validate_schema(
+            r#"{"$schema":"https://json-schema.org/draft/2020-12/schema","title":"T","type":"object",
+                "properties":{"at":{"type":"string","format":"date-time","description":"When"}}}"#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_type_lists_and_unique_items() {
+        let schema = r#"{"type":"object","properties":{
+            "v":{"type":["string","null"]},
+            "tags":{"type":"array","uniqueItems":true,"maxItems":3},
+            "score":{"type":"number","exclusiveMinimum":0,"exclusiveMaximum":1},
+            "kind":{"const":"paper"}
+        },"additionalProperties":{"type":"integer"}}"#;
+        let check = |fields: &str| {
+            validate_structured_metadata(
+                &Metadata::new("T", 1).with_fields("example:t", fields),
+                Some(schema),
+            )
+        };
+
+        assert!(check(r#"{"v":null,"tags":["a","b"],"score":0.5,"kind":"paper","n":3}"#).is_ok());
+        assert_eq!(invalid_path(check(r#"{"v":1}"#)), "/v");
+        assert_eq!(invalid_path(check(r#"{"tags":["a","a"]}"#)), "/tags/1");
+        assert_eq!(invalid_path(check(r#"{"tags":[1,2,3,4]}"#)), "/tags");
+        assert_eq!(invalid_path(check(r#"{"score":1}"#)), "/score");
+        assert_eq!(invalid_path(check(r#"{"kind":"book"}"#)), "/kind");
+        assert_eq!(invalid_path(check(r#"{"n":"3"}"#)), "/n");
+    }
+}
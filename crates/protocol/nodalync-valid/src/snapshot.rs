@@ -0,0 +1,171 @@
+//! State snapshot validation.
+//!
+//! A snapshot is signed by the node that took it, over every other field,
+//! so whoever relays it can't add, drop or reorder its announcements and
+//! peer records. Announcements carry their own publisher signatures, which
+//! are checked as each is imported.
+
+use nodalync_crypto::{peer_id_from_public_key, sign, verify, PrivateKey, Signature};
+use nodalync_types::{MAX_SNAPSHOT_ANNOUNCEMENTS, MAX_SNAPSHOT_PEERS};
+use nodalync_wire::{encode_payload, StateSnapshot};
+
+use crate::error::{ValidationError, ValidationResult};
+
+/// Construct the message bytes for snapshot signing/verification.
+///
+/// The message is the CBOR encoding of the snapshot with a zeroed
+/// signature.
+pub fn construct_snapshot_message(snapshot: &StateSnapshot) -> ValidationResult<Vec<u8>> {
+    let unsigned = StateSnapshot {
+        signature: Signature::from_bytes([0u8; 64]),
+        ..snapshot.clone()
+    };
+    encode_payload(&unsigned).map_err(|e| invalid(format!("encoding failed: {}", e)))
+}
+
+/// Sign a snapshot as its issuer.
+///
+/// Sets `issuer` and `issuer_key` from the key before signing.
+pub fn sign_snapshot(
+    private_key: &PrivateKey,
+    snapshot: &mut StateSnapshot,
+) -> ValidationResult<()> {
+    snapshot.issuer_key = private_key.public_key();
+    snapshot.issuer = peer_id_from_public_key(&snapshot.issuer_key);
+    let message = construct_snapshot_message(snapshot)?;
+    snapshot.signature = sign(private_key, &message);
+    Ok(())
+}
+
+/// Validate a snapshot and its issuer signature.
+///
+/// Checks:
+/// 1. `issuer` is derived from `issuer_key`
+/// 2. At most `MAX_SNAPSHOT_ANNOUNCEMENTS` announcements and
+///    `MAX_SNAPSHOT_PEERS` peer records
+/// 3. The signature verifies against `issuer_key`
+pub fn validate_snapshot(snapshot: &StateSnapshot) -> ValidationResult<()> {
+    if snapshot.issuer != peer_id_from_public_key(&snapshot.issuer_key) {
+        return Err(invalid("issuer does not match issuer key"));
+    }
+
+    if snapshot.announcements.len() > MAX_SNAPSHOT_ANNOUNCEMENTS {
+        return Err(invalid(format!(
+            "{} announcements exceeds maximum {}",
+            snapshot.announcements.len(),
+            MAX_SNAPSHOT_ANNOUNCEMENTS
+        )));
+    }
+    if snapshot.peers.len() > MAX_SNAPSHOT_PEERS {
+        return Err(invalid(format!(
+            "{} peers exceeds maximum {}",
+            snapshot.peers.len(),
+            MAX_SNAPSHOT_PEERS
+        )));
+    }
+
+    let message = construct_snapshot_message(snapshot)?;
+    if !verify(&snapshot.issuer_key, &message, &snapshot.signature) {
+        return Err(ValidationError::InvalidSnapshotSignature);
+    }
+
+    Ok(())
+}
+
+fn invalid(reason: impl Into<String>) -> ValidationError {
+    ValidationError::InvalidSnapshot {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, generate_identity};
+    use nodalync_types::{ContentType, L1Summary, PeerId};
+    use nodalync_wire::{AnnouncePayload, SnapshotPeer};
+
+    fn create_signed_snapshot() -> StateSnapshot {
+        let (private_key, public_key) = generate_identity();
+        let hash = content_hash(b"announced");
+        let mut snapshot = StateSnapshot {
+            issuer: PeerId([0u8; 20]),
+            issuer_key: public_key,
+            created_at: 1_000,
+            announcements: vec![AnnouncePayload {
+                hash,
+                content_type: ContentType::L0,
+                title: "Announced".to_string(),
+                l1_summary: L1Summary::empty(hash),
+                price: 100,
+                addresses: vec![],
+                publisher_peer_id: None,
+                publisher_key: None,
+                signature: None,
+                delegation: None,
+            }],
+            peers: vec![SnapshotPeer {
+                peer_id: peer_id_from_public_key(&public_key),
+                public_key,
+                addresses: vec!["/ip4/127.0.0.1/tcp/9000".to_string()],
+                libp2p_peer_id: None,
+                last_seen: 900,
+            }],
+            signature: Signature::from_bytes([0u8; 64]),
+        };
+        sign_snapshot(&private_key, &mut snapshot).unwrap();
+        snapshot
+    }
+
+    #[test]
+    fn test_valid_snapshot() {
+        let snapshot = create_signed_snapshot();
+        assert_eq!(
+            snapshot.issuer,
+            peer_id_from_public_key(&snapshot.issuer_key)
+        );
+        assert!(validate_snapshot(&snapshot).is_ok());
+    }
+
+    #[test]
+    fn test_tampered_snapshot() {
+        let snapshot = create_signed_snapshot();
+
+        let mut dropped = snapshot.clone();
+        dropped.announcements.clear();
+        assert!(matches!(
+            validate_snapshot(&dropped),
+            Err(ValidationError::InvalidSnapshotSignature)
+        ));
+
+        // Re-attributing it to another key doesn't verify either
+        let (_, other_key) = generate_identity();
+        let mut rekeyed = snapshot.clone();
+        rekeyed.issuer_key = other_key;
+        rekeyed.issuer = peer_id_from_public_key(&other_key);
+        assert!(matches!(
+            validate_snapshot(&rekeyed),
+            Err(ValidationError::InvalidSnapshotSignature)
+        ));
+
+        let mut mismatched = snapshot;
+        mismatched.issuer = PeerId([9u8; 20]);
+        assert!(matches!(
+            validate_snapshot(&mismatched),
+            Err(ValidationError::InvalidSnapshot { .. })
+        ));
+    }
+
+    #[test]
+    fn test_oversized_snapshot() {
+        let (private_key, _) = generate_identity();
+        let mut snapshot = create_signed_snapshot();
+        let peer = snapshot.peers[0].clone();
+        snapshot.peers = vec![peer; MAX_SNAPSHOT_PEERS + 1];
+        sign_snapshot(&private_key, &mut snapshot).unwrap();
+        assert!(matches!(
+            validate_snapshot(&snapshot),
+            Err(ValidationError::InvalidSnapshot { .. })
+        ));
+    }
+}
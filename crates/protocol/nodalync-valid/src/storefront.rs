@@ -0,0 +1,189 @@
+//! Storefront validation.
+//!
+//! A storefront is signed by its publisher over every other field, so
+//! whoever stores it in the DHT can't add, drop or reprice its listings.
+//! Listings are only the publisher's claims: a query is still checked
+//! against the content's manifest.
+
+use std::collections::HashSet;
+
+use nodalync_crypto::{peer_id_from_public_key, sign, verify, PrivateKey, Signature};
+use nodalync_types::{MAX_STOREFRONT_FEATURED, MAX_STOREFRONT_ITEMS, MAX_TITLE_LENGTH};
+use nodalync_wire::{encode_payload, Storefront};
+
+use crate::error::{ValidationError, ValidationResult};
+
+/// Construct the message bytes for storefront signing/verification.
+///
+/// The message is the CBOR encoding of the storefront with a zeroed
+/// signature.
+pub fn construct_storefront_message(storefront: &Storefront) -> ValidationResult<Vec<u8>> {
+    let unsigned = Storefront {
+        signature: Signature::from_bytes([0u8; 64]),
+        ..storefront.clone()
+    };
+    encode_payload(&unsigned).map_err(|e| invalid(format!("encoding failed: {}", e)))
+}
+
+/// Sign a storefront as its publisher.
+///
+/// Sets `publisher` and `publisher_key` from the key before signing.
+pub fn sign_storefront(
+    private_key: &PrivateKey,
+    storefront: &mut Storefront,
+) -> ValidationResult<()> {
+    storefront.publisher_key = private_key.public_key();
+    storefront.publisher = peer_id_from_public_key(&storefront.publisher_key);
+    let message = construct_storefront_message(storefront)?;
+    storefront.signature = sign(private_key, &message);
+    Ok(())
+}
+
+/// Validate a storefront and its publisher signature.
+///
+/// Checks:
+/// 1. `publisher` is derived from `publisher_key`
+/// 2. The name is at most `MAX_TITLE_LENGTH` characters
+/// 3. At most `MAX_STOREFRONT_ITEMS` items, of which at most
+///    `MAX_STOREFRONT_FEATURED` are featured
+/// 4. No item is listed twice, and each item's category is one of the
+///    storefront's
+/// 5. The signature verifies against `publisher_key`
+pub fn validate_storefront(storefront: &Storefront) -> ValidationResult<()> {
+    if storefront.publisher != peer_id_from_public_key(&storefront.publisher_key) {
+        return Err(invalid("publisher does not match publisher key"));
+    }
+
+    if storefront.name.chars().count() > MAX_TITLE_LENGTH {
+        return Err(invalid(format!(
+            "name exceeds {} characters",
+            MAX_TITLE_LENGTH
+        )));
+    }
+
+    if storefront.items.len() > MAX_STOREFRONT_ITEMS {
+        return Err(invalid(format!(
+            "{} items exceeds maximum {}",
+            storefront.items.len(),
+            MAX_STOREFRONT_ITEMS
+        )));
+    }
+    let featured = storefront.featured().count();
+    if featured > MAX_STOREFRONT_FEATURED {
+        return Err(invalid(format!(
+            "{} featured items exceeds maximum {}",
+            featured, MAX_STOREFRONT_FEATURED
+        )));
+    }
+
+    let mut seen = HashSet::new();
+    for item in &storefront.items {
+        if !seen.insert(item.hash) {
+            return Err(invalid(format!("{} listed twice", item.hash)));
+        }
+        if let Some(category) = &item.category {
+            if !storefront.categories.contains(category) {
+                return Err(invalid(format!("unknown category {:?}", category)));
+            }
+        }
+    }
+
+    let message = construct_storefront_message(storefront)?;
+    if !verify(&storefront.publisher_key, &message, &storefront.signature) {
+        return Err(ValidationError::InvalidStorefrontSignature);
+    }
+
+    Ok(())
+}
+
+fn invalid(reason: impl Into<String>) -> ValidationError {
+    ValidationError::InvalidStorefront {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, generate_identity};
+    use nodalync_types::{ContentType, PeerId};
+    use nodalync_wire::StorefrontItem;
+
+    fn item(content: &[u8], featured: bool) -> StorefrontItem {
+        StorefrontItem {
+            hash: content_hash(content),
+            title: "Listed".to_string(),
+            content_type: ContentType::L0,
+            price: 100,
+            category: Some("guides".to_string()),
+            featured,
+        }
+    }
+
+    fn create_signed_storefront(private_key: &PrivateKey) -> Storefront {
+        let mut storefront = Storefront {
+            publisher: PeerId([0u8; 20]),
+            publisher_key: private_key.public_key(),
+            name: "Field Guides".to_string(),
+            created_at: 1_000,
+            categories: vec!["guides".to_string()],
+            items: vec![item(b"first", true), item(b"second", false)],
+            signature: Signature::from_bytes([0u8; 64]),
+        };
+        sign_storefront(private_key, &mut storefront).unwrap();
+        storefront
+    }
+
+    #[test]
+    fn test_valid_storefront() {
+        let (private_key, public_key) = generate_identity();
+        let storefront = create_signed_storefront(&private_key);
+        assert_eq!(storefront.publisher, peer_id_from_public_key(&public_key));
+        assert!(validate_storefront(&storefront).is_ok());
+    }
+
+    #[test]
+    fn test_tampered_storefront() {
+        let (private_key, _) = generate_identity();
+        let storefront = create_signed_storefront(&private_key);
+
+        let mut repriced = storefront.clone();
+        repriced.items[0].price = 1;
+        assert!(matches!(
+            validate_storefront(&repriced),
+            Err(ValidationError::InvalidStorefrontSignature)
+        ));
+
+        let mut mismatched = storefront;
+        mismatched.publisher = PeerId([9u8; 20]);
+        assert!(matches!(
+            validate_storefront(&mismatched),
+            Err(ValidationError::InvalidStorefront { .. })
+        ));
+    }
+
+    #[test]
+    fn test_malformed_storefront() {
+        let (private_key, _) = generate_identity();
+
+        let mut duplicated = create_signed_storefront(&private_key);
+        duplicated.items.push(item(b"first", false));
+        sign_storefront(&private_key, &mut duplicated).unwrap();
+        assert!(validate_storefront(&duplicated).is_err());
+
+        let mut uncategorized = create_signed_storefront(&private_key);
+        uncategorized.categories.clear();
+        sign_storefront(&private_key, &mut uncategorized).unwrap();
+        assert!(validate_storefront(&uncategorized).is_err());
+
+        let mut overfeatured = create_signed_storefront(&private_key);
+        overfeatured.items = (0..=MAX_STOREFRONT_FEATURED)
+            .map(|i| item(&i.to_be_bytes(), true))
+            .collect();
+        sign_storefront(&private_key, &mut overfeatured).unwrap();
+        assert!(matches!(
+            validate_storefront(&overfeatured),
+            Err(ValidationError::InvalidStorefront { .. })
+        ));
+    }
+}
@@ -0,0 +1,119 @@
+//! Tombstone validation.
+//!
+//! A tombstone makes peers drop and refuse to serve a content hash, so it
+//! must only be honoured from the content owner; otherwise anyone could
+//! withdraw anyone else's content. Validation checks the hash, that the
+//! key belongs to the owner, the signature, and that the owner is the
+//! content's known owner when the caller knows it.
+
+use nodalync_crypto::{peer_id_from_public_key, sign, verify, PrivateKey};
+use nodalync_types::{PeerId, Tombstone};
+
+use crate::error::{ValidationError, ValidationResult};
+
+/// Sign a tombstone as the content owner.
+///
+/// Recomputes the hash from the terms before signing it.
+pub fn sign_tombstone(private_key: &PrivateKey, tombstone: &mut Tombstone) {
+    tombstone.hash = tombstone.compute_hash();
+    tombstone.signature = sign(private_key, &tombstone.hash.0);
+}
+
+/// Validate a tombstone and its owner signature.
+///
+/// Checks:
+/// 1. `hash` matches the terms
+/// 2. `owner` is derived from `owner_key`
+/// 3. The signature over `hash` verifies against `owner_key`
+/// 4. `owner` is `content_owner`, if the content's owner is known
+pub fn validate_tombstone(
+    tombstone: &Tombstone,
+    content_owner: Option<&PeerId>,
+) -> ValidationResult<()> {
+    if tombstone.hash != tombstone.compute_hash() {
+        return Err(invalid("hash does not match the tombstone terms"));
+    }
+
+    if tombstone.owner != peer_id_from_public_key(&tombstone.owner_key) {
+        return Err(invalid("owner does not match owner key"));
+    }
+
+    if !verify(
+        &tombstone.owner_key,
+        &tombstone.hash.0,
+        &tombstone.signature,
+    ) {
+        return Err(ValidationError::InvalidTombstoneSignature);
+    }
+
+    if content_owner.is_some_and(|owner| *owner != tombstone.owner) {
+        return Err(invalid("not issued by the content owner"));
+    }
+
+    Ok(())
+}
+
+fn invalid(reason: impl Into<String>) -> ValidationError {
+    ValidationError::InvalidTombstone {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::{content_hash, generate_identity};
+
+    fn create_signed_tombstone() -> (Tombstone, PrivateKey) {
+        let (private_key, public_key) = generate_identity();
+        let mut tombstone = Tombstone::new(
+            content_hash(b"withdrawn"),
+            peer_id_from_public_key(&public_key),
+            public_key,
+            1_000,
+        );
+        sign_tombstone(&private_key, &mut tombstone);
+        (tombstone, private_key)
+    }
+
+    #[test]
+    fn test_valid_tombstone() {
+        let (tombstone, _) = create_signed_tombstone();
+        assert!(validate_tombstone(&tombstone, None).is_ok());
+        assert!(validate_tombstone(&tombstone, Some(&tombstone.owner)).is_ok());
+    }
+
+    #[test]
+    fn test_tombstone_from_non_owner() {
+        // Validly signed, but not by the content's owner
+        let (tombstone, _) = create_signed_tombstone();
+        assert!(matches!(
+            validate_tombstone(&tombstone, Some(&PeerId([1u8; 20]))),
+            Err(ValidationError::InvalidTombstone { .. })
+        ));
+    }
+
+    #[test]
+    fn test_tombstone_tampering() {
+        let (tombstone, _) = create_signed_tombstone();
+
+        // Retargeting without re-hashing
+        let mut changed = tombstone.clone();
+        changed.content_hash = content_hash(b"someone else's content");
+        assert!(validate_tombstone(&changed, None).is_err());
+
+        // Re-hashed but not re-signed
+        changed.hash = changed.compute_hash();
+        assert!(matches!(
+            validate_tombstone(&changed, None),
+            Err(ValidationError::InvalidTombstoneSignature)
+        ));
+
+        // Signed with another key claiming the owner's identity
+        let (other_key, other_public) = generate_identity();
+        let mut forged = tombstone.clone();
+        forged.owner_key = other_public;
+        sign_tombstone(&other_key, &mut forged);
+        assert!(validate_tombstone(&forged, None).is_err());
+    }
+}
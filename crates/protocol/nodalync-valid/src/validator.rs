@@ -3,17 +3,21 @@
 //! This module provides the main `Validator` trait that combines all
 //! validation functions, as well as a default implementation.
 
-use nodalync_crypto::{PublicKey, Timestamp};
-use nodalync_types::{Channel, Manifest, Payment, PeerId};
-use nodalync_wire::Message;
+use nodalync_crypto::{Hash, PublicKey, Timestamp};
+use nodalync_types::{CapabilityToken, Channel, Manifest, Metadata, Payment, PeerId};
+use nodalync_wire::{AnnouncePayload, Message};
 
-use crate::access::validate_access_with_owner_bypass;
+use crate::access::{is_owner, validate_access_with_groups, validate_embargo};
+use crate::announce::validate_announcement;
+use crate::capability::validate_capability;
 use crate::content::validate_content;
 use crate::error::ValidationResult;
+use crate::group::GroupResolver;
 use crate::message::validate_message;
 use crate::payment::{validate_payment, BondChecker, PublicKeyLookup};
 use crate::provenance::validate_provenance;
-use crate::version::validate_version;
+use crate::schema::validate_structured_metadata;
+use crate::version::{find_version_forks, validate_version, VersionLookup};
 
 /// Trait for validating protocol entities.
 ///
@@ -35,6 +39,17 @@ pub trait Validator {
         previous: Option<&Manifest>,
     ) -> ValidationResult<()>;
 
+    /// Validate version constraints and detect forks.
+    ///
+    /// Runs the §9.2 checks, then returns the hashes of the versions in
+    /// `known` that fork with `manifest` (same lineage and version number).
+    fn validate_version_in_lineage(
+        &self,
+        manifest: &Manifest,
+        previous: Option<&Manifest>,
+        known: &dyn VersionLookup,
+    ) -> ValidationResult<Vec<Hash>>;
+
     /// Validate provenance chain.
     ///
     /// See §9.3 for validation rules.
@@ -61,8 +76,44 @@ pub trait Validator {
 
     /// Validate access permissions.
     ///
-    /// See §9.6 for validation rules.
+    /// See §9.6 for validation rules. Content under embargo is denied to
+    /// everyone but its owner.
     fn validate_access(&self, requester: &PeerId, manifest: &Manifest) -> ValidationResult<()>;
+
+    /// Validate access permissions, resolving `access.groups` membership
+    /// through `groups`.
+    fn validate_access_with_groups(
+        &self,
+        requester: &PeerId,
+        manifest: &Manifest,
+        groups: &dyn GroupResolver,
+    ) -> ValidationResult<()>;
+
+    /// Validate a capability token presented in place of normal access.
+    ///
+    /// The token must be issued by the content owner, unexpired, and bound
+    /// to the requester if it names a grantee.
+    fn validate_capability(
+        &self,
+        token: &CapabilityToken,
+        requester: &PeerId,
+        manifest: &Manifest,
+    ) -> ValidationResult<()>;
+
+    /// Validate structured metadata fields.
+    ///
+    /// `schema` is the schema `metadata.schema` resolved to, if any.
+    fn validate_structured_metadata(
+        &self,
+        metadata: &Metadata,
+        schema: Option<&str>,
+    ) -> ValidationResult<()>;
+
+    /// Validate an announcement's publisher signature.
+    ///
+    /// Returns the verified publisher, or `None` if the announcement is
+    /// unsigned. A replica's announcement verifies as its primary's.
+    fn validate_announcement(&self, payload: &AnnouncePayload) -> ValidationResult<Option<PeerId>>;
 }
 
 /// Configuration for the default validator.
@@ -135,14 +186,26 @@ where
         }
     }
 
+    /// Shared body of the access checks: owners always pass, everyone
+    /// else is subject to the embargo and the §9.6 rules.
+    fn check_access(
+        &self,
+        requester: &PeerId,
+        manifest: &Manifest,
+        groups: Option<&dyn GroupResolver>,
+    ) -> ValidationResult<()> {
+        if is_owner(requester, manifest) {
+            return Ok(());
+        }
+        validate_embargo(manifest, self.current_time())?;
+        validate_access_with_groups(requester, manifest, Some(&self.bond_checker), groups)
+    }
+
     /// Get the current timestamp.
     fn current_time(&self) -> Timestamp {
-        self.config.current_time.unwrap_or_else(|| {
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64
-        })
+        self.config
+            .current_time
+            .unwrap_or_else(nodalync_crypto::current_timestamp)
     }
 }
 
@@ -169,6 +232,16 @@ where
         validate_version(manifest, previous)
     }
 
+    fn validate_version_in_lineage(
+        &self,
+        manifest: &Manifest,
+        previous: Option<&Manifest>,
+        known: &dyn VersionLookup,
+    ) -> ValidationResult<Vec<Hash>> {
+        validate_version(manifest, previous)?;
+        Ok(find_version_forks(manifest, known))
+    }
+
     fn validate_provenance(
         &self,
         manifest: &Manifest,
@@ -196,6 +269,7 @@ where
             manifest,
             payer_pubkey.as_ref(),
             payment_nonce,
+            None,
         )
     }
 
@@ -207,7 +281,37 @@ where
     }
 
     fn validate_access(&self, requester: &PeerId, manifest: &Manifest) -> ValidationResult<()> {
-        validate_access_with_owner_bypass(requester, manifest, Some(&self.bond_checker))
+        self.check_access(requester, manifest, None)
+    }
+
+    fn validate_access_with_groups(
+        &self,
+        requester: &PeerId,
+        manifest: &Manifest,
+        groups: &dyn GroupResolver,
+    ) -> ValidationResult<()> {
+        self.check_access(requester, manifest, Some(groups))
+    }
+
+    fn validate_capability(
+        &self,
+        token: &CapabilityToken,
+        requester: &PeerId,
+        manifest: &Manifest,
+    ) -> ValidationResult<()> {
+        validate_capability(token, requester, manifest, self.current_time())
+    }
+
+    fn validate_structured_metadata(
+        &self,
+        metadata: &Metadata,
+        schema: Option<&str>,
+    ) -> ValidationResult<()> {
+        validate_structured_metadata(metadata, schema)
+    }
+
+    fn validate_announcement(&self, payload: &AnnouncePayload) -> ValidationResult<Option<PeerId>> {
+        validate_announcement(payload, self.current_time())
     }
 }
 
@@ -305,6 +409,57 @@ mod tests {
         assert!(matches!(result, Err(ValidationError::ContentPrivate)));
     }
 
+    #[test]
+    fn test_default_validator_access_embargoed() {
+        let validator = DefaultValidator::with_config(ValidatorConfig::new().with_fixed_time(1000));
+        let mut manifest = create_test_manifest(b"Content");
+        manifest.visibility = Visibility::Shared;
+        manifest.access.publish_at = Some(2000);
+        let requester = test_peer_id();
+
+        let result = validator.validate_access(&requester, &manifest);
+        assert!(matches!(
+            result,
+            Err(ValidationError::Embargoed { publish_at: 2000 })
+        ));
+
+        // The owner can always access
+        assert!(validator
+            .validate_access(&manifest.owner, &manifest)
+            .is_ok());
+
+        let validator = DefaultValidator::with_config(ValidatorConfig::new().with_fixed_time(2000));
+        assert!(validator.validate_access(&requester, &manifest).is_ok());
+    }
+
+    #[test]
+    fn test_default_validator_access_groups() {
+        struct Members(Vec<PeerId>);
+        impl GroupResolver for Members {
+            fn is_member(&self, _: &nodalync_crypto::Hash, peer: &PeerId) -> bool {
+                self.0.contains(peer)
+            }
+        }
+
+        let validator = DefaultValidator::new();
+        let mut manifest = create_test_manifest(b"Content");
+        manifest.visibility = Visibility::Unlisted;
+        manifest.access = nodalync_types::AccessControl::with_groups(vec![content_hash(b"g")]);
+        let member = test_peer_id();
+        let groups = Members(vec![member]);
+
+        assert!(validator
+            .validate_access_with_groups(&member, &manifest, &groups)
+            .is_ok());
+        assert!(validator.validate_access(&member, &manifest).is_err());
+        assert!(validator
+            .validate_access_with_groups(&test_peer_id(), &manifest, &groups)
+            .is_err());
+        assert!(validator
+            .validate_access_with_groups(&manifest.owner, &manifest, &Members(vec![]))
+            .is_ok());
+    }
+
     #[test]
     fn test_validator_with_fixed_time() {
         let config = ValidatorConfig::new().with_fixed_time(1000000);
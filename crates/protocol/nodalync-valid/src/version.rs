@@ -5,11 +5,48 @@
 //! - v2+: previous is Some, root equals previous.root
 //! - Version number increments by 1
 //! - Timestamp is after previous timestamp
+//!
+//! It also detects forks: two versions of one lineage with the same number.
 
+use nodalync_crypto::Hash;
 use nodalync_types::Manifest;
 
 use crate::error::{ValidationError, ValidationResult};
 
+/// Source of the versions already known for a version root, such as the
+/// local manifest store.
+pub trait VersionLookup {
+    /// Known manifests with this version root.
+    fn versions(&self, root: &Hash) -> Vec<Manifest>;
+}
+
+impl VersionLookup for Vec<Manifest> {
+    fn versions(&self, root: &Hash) -> Vec<Manifest> {
+        self.iter()
+            .filter(|m| m.version.root == *root)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Find the known versions that fork with `manifest`.
+///
+/// A fork is another version of the same lineage (same root and owner)
+/// with the same version number but different content. Forks are not
+/// invalid in themselves; callers flag them and pick a canonical version.
+pub fn find_version_forks(manifest: &Manifest, known: &dyn VersionLookup) -> Vec<Hash> {
+    known
+        .versions(&manifest.version.root)
+        .into_iter()
+        .filter(|m| {
+            m.owner == manifest.owner
+                && m.version.number == manifest.version.number
+                && m.hash != manifest.hash
+        })
+        .map(|m| m.hash)
+        .collect()
+}
+
 /// Validate version constraints for a manifest.
 ///
 /// Checks all version validation rules from §9.2:
@@ -323,4 +360,27 @@ mod tests {
         // v3's root should still be v1's hash
         assert_eq!(v3.version.root, v1.hash);
     }
+
+    #[test]
+    fn test_find_version_forks() {
+        let v1 = create_test_manifest(b"Content v1", 1000);
+        let branch = |content: &[u8], timestamp| {
+            let mut m = create_test_manifest(content, timestamp);
+            m.owner = v1.owner;
+            m.version = Version::new_from_previous(&v1.version, v1.hash, timestamp);
+            m
+        };
+        let v2a = branch(b"Content v2a", 2000);
+        let v2b = branch(b"Content v2b", 3000);
+
+        let known = vec![v1.clone(), v2a.clone()];
+        assert!(find_version_forks(&v1, &known).is_empty());
+        assert!(find_version_forks(&v2a, &known).is_empty());
+        assert_eq!(find_version_forks(&v2b, &known), vec![v2a.hash]);
+
+        // Another owner's manifest claiming the root is not a fork
+        let mut other = v2b.clone();
+        other.owner = create_test_manifest(b"x", 1).owner;
+        assert!(find_version_forks(&other, &known).is_empty());
+    }
 }
@@ -0,0 +1,89 @@
+//! Wire encoding and decoding benchmarks.
+//!
+//! Run with `cargo bench -p nodalync-wire`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use nodalync_crypto::{content_hash, generate_identity, peer_id_from_public_key};
+use nodalync_types::{ContentType, L1Summary};
+use nodalync_wire::{
+    create_message, decode_message, decode_payload, encode_message, encode_payload,
+    AnnouncePayload, MessageType,
+};
+
+fn announce_payload() -> AnnouncePayload {
+    let hash = content_hash(b"benchmark content");
+    AnnouncePayload {
+        hash,
+        content_type: ContentType::L0,
+        title: "Benchmark announcement with a reasonably long title".to_string(),
+        l1_summary: L1Summary::new(
+            hash,
+            12,
+            Vec::new(),
+            vec![
+                "rust".to_string(),
+                "p2p".to_string(),
+                "benchmarks".to_string(),
+            ],
+            "A short summary of the announced content.",
+        ),
+        price: 100_000,
+        addresses: vec![
+            "/ip4/192.168.1.10/tcp/9000".to_string(),
+            "/ip6/::1/tcp/9000".to_string(),
+        ],
+        publisher_peer_id: Some("12D3KooWBenchmarkPeer".to_string()),
+        publisher_key: None,
+        signature: None,
+        delegation: None,
+    }
+}
+
+fn bench_payload(c: &mut Criterion) {
+    let payload = announce_payload();
+    let bytes = encode_payload(&payload).unwrap();
+
+    let mut group = c.benchmark_group("payload");
+    group.bench_function("encode_announce", |b| {
+        b.iter(|| encode_payload(black_box(&payload)).unwrap())
+    });
+    group.bench_function("decode_announce", |b| {
+        b.iter(|| decode_payload::<AnnouncePayload>(black_box(&bytes)).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_message(c: &mut Criterion) {
+    let (private_key, public_key) = generate_identity();
+    let sender = peer_id_from_public_key(&public_key);
+
+    let mut encode = c.benchmark_group("message_encode");
+    let mut messages = Vec::new();
+    for size in [256, 64 << 10, 1 << 20] {
+        let message = create_message(
+            MessageType::QueryResponse,
+            vec![0x5Au8; size],
+            sender,
+            1_700_000_000_000,
+            &private_key,
+        );
+        encode.throughput(Throughput::Bytes(size as u64));
+        encode.bench_with_input(BenchmarkId::from_parameter(size), &message, |b, msg| {
+            b.iter(|| encode_message(black_box(msg)).unwrap())
+        });
+        messages.push((size, encode_message(&message).unwrap()));
+    }
+    encode.finish();
+
+    let mut decode = c.benchmark_group("message_decode");
+    for (size, bytes) in &messages {
+        decode.throughput(Throughput::Bytes(*size as u64));
+        decode.bench_with_input(BenchmarkId::from_parameter(size), bytes, |b, bytes| {
+            b.iter(|| decode_message(black_box(bytes)).unwrap())
+        });
+    }
+    decode.finish();
+}
+
+criterion_group!(benches, bench_payload, bench_message);
+criterion_main!(benches);
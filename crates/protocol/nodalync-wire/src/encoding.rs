@@ -20,6 +20,7 @@
 
 use nodalync_crypto::{Hash, PeerId, PrivateKey, Signature, Timestamp};
 use nodalync_types::constants::{MAX_MESSAGE_SIZE, PROTOCOL_MAGIC, PROTOCOL_VERSION};
+use nodalync_types::CapabilityToken;
 use serde::{de::DeserializeOwned, Serialize};
 use sha2::{Digest, Sha256};
 
@@ -28,7 +29,10 @@ use crate::message::{Message, MessageType};
 use crate::payload::ChannelBalances;
 
 /// Minimum message size: magic(1) + version(1) + type(2) + timestamp(8) + sender(20) + length(4) + signature(64) = 100 bytes
-const MIN_MESSAGE_SIZE: usize = 1 + 1 + 2 + 8 + 20 + 4 + 64;
+const MIN_MESSAGE_SIZE: usize = MESSAGE_HEADER_SIZE + 64;
+
+/// Header size: magic(1) + version(1) + type(2) + timestamp(8) + sender(20) + length(4) = 36 bytes
+const MESSAGE_HEADER_SIZE: usize = 1 + 1 + 2 + 8 + 20 + 4;
 
 /// Domain separator for content hashing
 const DOMAIN_CONTENT: u8 = 0x00;
@@ -39,6 +43,9 @@ const DOMAIN_MESSAGE: u8 = 0x01;
 /// Domain separator for channel state hashing
 const DOMAIN_CHANNEL_STATE: u8 = 0x02;
 
+/// Domain separator for storefront DHT keys
+const DOMAIN_STOREFRONT: u8 = 0x05;
+
 // =============================================================================
 // Hash Functions
 // =============================================================================
@@ -88,6 +95,18 @@ pub fn channel_state_hash(channel_id: &Hash, nonce: u64, balances: &ChannelBalan
     Hash(hasher.finalize().into())
 }
 
+/// Compute the DHT key a publisher's storefront is stored under.
+///
+/// Uses domain separator `0x05`, so it can't collide with a content hash.
+///
+/// `H(0x05 || publisher)`
+pub fn storefront_key(publisher: &PeerId) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([DOMAIN_STOREFRONT]);
+    hasher.update(publisher.0);
+    Hash(hasher.finalize().into())
+}
+
 // =============================================================================
 // Payload Encoding/Decoding
 // =============================================================================
@@ -115,6 +134,19 @@ pub fn decode_payload<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, DecodeErro
     ciborium::from_reader(bytes).map_err(|e| DecodeError::PayloadDecodeFailed(e.to_string()))
 }
 
+/// Encode a capability token as a base58 string, for sharing out of band.
+pub fn encode_capability(token: &CapabilityToken) -> Result<String, EncodeError> {
+    Ok(bs58::encode(encode_payload(token)?).into_string())
+}
+
+/// Decode a capability token produced by [`encode_capability`].
+pub fn decode_capability(encoded: &str) -> Result<CapabilityToken, DecodeError> {
+    let bytes = bs58::decode(encoded.trim())
+        .into_vec()
+        .map_err(|e| DecodeError::PayloadDecodeFailed(e.to_string()))?;
+    decode_payload(&bytes)
+}
+
 // =============================================================================
 // Message Encoding/Decoding
 // =============================================================================
@@ -133,39 +165,42 @@ pub fn decode_payload<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, DecodeErro
 /// [signature: 64 bytes]   # Ed25519 signature
 /// ```
 pub fn encode_message(msg: &Message) -> Result<Vec<u8>, EncodeError> {
-    let payload_len = msg.payload.len();
-    // Header: magic(1) + version(1) + type(2) + timestamp(8) + sender(20) + length(4) + signature(64) = 100
-    let total_len = 100 + payload_len;
-
-    let mut buf = Vec::with_capacity(total_len);
-
-    // Magic byte
-    buf.push(PROTOCOL_MAGIC);
-
-    // Version
-    buf.push(msg.version);
-
-    // Message type (big-endian)
-    buf.extend_from_slice(&msg.message_type.to_u16().to_be_bytes());
-
-    // Timestamp (big-endian)
-    buf.extend_from_slice(&msg.timestamp.to_be_bytes());
-
-    // Sender peer ID (20 bytes)
-    buf.extend_from_slice(&msg.sender.0);
-
-    // Payload length (big-endian)
-    buf.extend_from_slice(&(payload_len as u32).to_be_bytes());
-
-    // Payload
+    let mut buf = Vec::with_capacity(MIN_MESSAGE_SIZE + msg.payload.len());
+    buf.extend_from_slice(&message_header(msg));
     buf.extend_from_slice(&msg.payload);
+    buf.extend_from_slice(&msg.signature.0);
 
-    // Signature
+    Ok(buf)
+}
+
+/// Encode a message to wire format, reusing its payload buffer.
+///
+/// Produces the same bytes as [`encode_message`], but frames the payload in
+/// place rather than copying it into a new buffer, so a large payload (such
+/// as a query response) is never held twice.
+pub fn encode_message_owned(msg: Message) -> Result<Vec<u8>, EncodeError> {
+    let header = message_header(&msg);
+    let mut buf = msg.payload;
+    buf.reserve_exact(MIN_MESSAGE_SIZE);
+    buf.splice(0..0, header);
     buf.extend_from_slice(&msg.signature.0);
 
     Ok(buf)
 }
 
+/// Everything before the payload: magic, version, type, timestamp, sender
+/// and payload length, in wire order.
+fn message_header(msg: &Message) -> [u8; MESSAGE_HEADER_SIZE] {
+    let mut header = [0u8; MESSAGE_HEADER_SIZE];
+    header[0] = PROTOCOL_MAGIC;
+    header[1] = msg.version;
+    header[2..4].copy_from_slice(&msg.message_type.to_u16().to_be_bytes());
+    header[4..12].copy_from_slice(&msg.timestamp.to_be_bytes());
+    header[12..32].copy_from_slice(&msg.sender.0);
+    header[32..36].copy_from_slice(&(msg.payload.len() as u32).to_be_bytes());
+    header
+}
+
 /// Decode a message from wire format.
 ///
 /// Wire format (v2 - includes sender and timestamp):
@@ -433,6 +468,39 @@ mod tests {
         assert_ne!(h1, h2);
     }
 
+    #[test]
+    fn test_storefront_key() {
+        let publisher = PeerId([7u8; 20]);
+        assert_eq!(storefront_key(&publisher), storefront_key(&publisher));
+        assert_ne!(
+            storefront_key(&publisher),
+            storefront_key(&PeerId([8u8; 20]))
+        );
+        assert_ne!(storefront_key(&publisher), content_hash(&publisher.0));
+    }
+
+    #[test]
+    fn test_capability_string_roundtrip() {
+        let (_, public_key, peer_id) = test_keypair();
+        let token = CapabilityToken::new(
+            crypto_hash(b"private"),
+            peer_id,
+            public_key,
+            Some(PeerId([7u8; 20])),
+            1_000,
+            2_000,
+        );
+
+        let encoded = encode_capability(&token).unwrap();
+        assert_eq!(decode_capability(&encoded).unwrap(), token);
+        assert_eq!(
+            decode_capability(&format!(" {}\n", encoded)).unwrap(),
+            token
+        );
+        assert!(decode_capability("not-base58!").is_err());
+        assert!(decode_capability("3mJr7AoUXx2Wqd").is_err());
+    }
+
     #[test]
     fn test_encode_decode_payload_roundtrip() {
         use crate::payload::PingPayload;
@@ -609,6 +677,9 @@ mod tests {
             price: 100,
             addresses: vec!["/ip4/127.0.0.1/tcp/9000".to_string()],
             publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
         };
 
         // Encode multiple times - should be identical
@@ -672,6 +743,22 @@ mod tests {
         assert_eq!(decoded.message_type, MessageType::Announce);
     }
 
+    #[test]
+    fn test_encode_message_owned() {
+        let (private_key, _public_key, peer_id) = test_keypair();
+        for payload in [Vec::new(), vec![0xCDu8; 100_000]] {
+            let msg = create_message(
+                MessageType::QueryResponse,
+                payload,
+                peer_id,
+                1234567890000,
+                &private_key,
+            );
+            let encoded = encode_message(&msg).unwrap();
+            assert_eq!(encode_message_owned(msg).unwrap(), encoded);
+        }
+    }
+
     #[test]
     fn test_encode_message_all_types_roundtrip() {
         let (private_key, _public_key, peer_id) = test_keypair();
@@ -693,11 +780,27 @@ mod tests {
             MessageType::ChannelClose,
             MessageType::ChannelDispute,
             MessageType::ChannelCloseAck,
+            MessageType::ChannelSync,
+            MessageType::ChannelSyncResponse,
             MessageType::SettleBatch,
             MessageType::SettleConfirm,
             MessageType::Ping,
             MessageType::Pong,
             MessageType::PeerInfo,
+            MessageType::InvoiceRequest,
+            MessageType::Invoice,
+            MessageType::InvoicePay,
+            MessageType::InvoiceAck,
+            MessageType::GroupRequest,
+            MessageType::GroupResponse,
+            MessageType::SnapshotRequest,
+            MessageType::SnapshotResponse,
+            MessageType::SyncPush,
+            MessageType::SyncPushAck,
+            MessageType::SyncPull,
+            MessageType::SyncPullResponse,
+            MessageType::AvailabilityCheck,
+            MessageType::AvailabilityResult,
         ];
         for msg_type in types {
             let msg = create_message(
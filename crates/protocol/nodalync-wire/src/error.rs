@@ -3,6 +3,7 @@
 //! This module defines errors that can occur during message encoding,
 //! decoding, and format validation.
 
+use nodalync_types::ErrorCode;
 use thiserror::Error;
 
 /// Errors that can occur when encoding a message or payload.
@@ -129,6 +130,69 @@ pub enum FormatError {
     InvalidPayload,
 }
 
+// Malformed messages have no protocol error code of their own: signature
+// and ID mismatches map to the integrity codes, oversized payloads to
+// `ContentTooLarge`, and everything else to `InternalError`.
+
+impl EncodeError {
+    /// Get the protocol error code for this error.
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            Self::PayloadTooLarge { .. } => ErrorCode::ContentTooLarge,
+            Self::Cbor(_) => ErrorCode::InternalError,
+        }
+    }
+}
+
+impl DecodeError {
+    /// Get the protocol error code for this error.
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            Self::SignatureMismatch => ErrorCode::InvalidSignature,
+            Self::IdMismatch => ErrorCode::InvalidHash,
+            Self::InvalidMagic { .. }
+            | Self::InvalidVersion { .. }
+            | Self::InvalidMessageType(_)
+            | Self::PayloadDecodeFailed(_)
+            | Self::TruncatedMessage { .. }
+            | Self::Io(_) => ErrorCode::InternalError,
+        }
+    }
+}
+
+impl FormatError {
+    /// Get the protocol error code for this error.
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidSignature => ErrorCode::InvalidSignature,
+            Self::InvalidId => ErrorCode::InvalidHash,
+            Self::InvalidVersion(_)
+            | Self::InvalidType
+            | Self::TimestampOutOfRange(_)
+            | Self::InvalidSender
+            | Self::InvalidPayload => ErrorCode::InternalError,
+        }
+    }
+}
+
+impl From<&EncodeError> for ErrorCode {
+    fn from(error: &EncodeError) -> Self {
+        error.error_code()
+    }
+}
+
+impl From<&DecodeError> for ErrorCode {
+    fn from(error: &DecodeError) -> Self {
+        error.error_code()
+    }
+}
+
+impl From<&FormatError> for ErrorCode {
+    fn from(error: &FormatError) -> Self {
+        error.error_code()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -182,4 +246,22 @@ mod tests {
         let err = FormatError::TimestampOutOfRange(123456);
         assert!(format!("{}", err).contains("123456"));
     }
+
+    #[test]
+    fn test_error_code_mapping() {
+        let err = EncodeError::PayloadTooLarge {
+            size: 1000,
+            max: 500,
+        };
+        assert_eq!(err.error_code(), ErrorCode::ContentTooLarge);
+        assert_eq!(
+            ErrorCode::from(&DecodeError::SignatureMismatch),
+            ErrorCode::InvalidSignature
+        );
+        assert_eq!(FormatError::InvalidId.error_code(), ErrorCode::InvalidHash);
+        assert_eq!(
+            FormatError::InvalidSender.error_code(),
+            ErrorCode::InternalError
+        );
+    }
 }
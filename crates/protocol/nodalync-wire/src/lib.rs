@@ -28,13 +28,18 @@
 //!
 //! | Category   | Code Range | Messages |
 //! |------------|------------|----------|
-//! | Discovery  | 0x01xx     | Announce, AnnounceUpdate, Search, SearchResponse |
+//! | Discovery  | 0x01xx     | Announce, AnnounceUpdate, Tombstone, Report, Revocation, FraudProof, Search, SearchResponse |
 //! | Preview    | 0x02xx     | PreviewRequest, PreviewResponse |
 //! | Query      | 0x03xx     | QueryRequest, QueryResponse, QueryError |
 //! | Version    | 0x04xx     | VersionRequest, VersionResponse |
 //! | Channel    | 0x05xx     | ChannelOpen, ChannelAccept, ChannelUpdate, ChannelClose, ChannelDispute |
 //! | Settlement | 0x06xx     | SettleBatch, SettleConfirm |
 //! | Peer       | 0x07xx     | Ping, Pong, PeerInfo |
+//! | Invoice    | 0x08xx     | InvoiceRequest, Invoice, InvoicePay, InvoiceAck |
+//! | Group      | 0x09xx     | GroupRequest, GroupResponse |
+//! | Snapshot   | 0x0Axx     | SnapshotRequest, SnapshotResponse |
+//! | Sync       | 0x0Bxx     | SyncPush, SyncPushAck, SyncPull, SyncPullResponse |
+//! | Availability | 0x0Cxx   | AvailabilityCheck, AvailabilityResult |
 //!
 //! # Example
 //!
@@ -113,15 +118,15 @@ pub use message::{Message, MessageType};
 
 // Encoding functions
 pub use encoding::{
-    channel_state_hash, content_hash, create_message, decode_message, decode_payload,
-    encode_message, encode_payload, message_hash, validate_message_format,
-    verify_message_signature,
+    channel_state_hash, content_hash, create_message, decode_capability, decode_message,
+    decode_payload, encode_capability, encode_message, encode_message_owned, encode_payload,
+    message_hash, storefront_key, validate_message_format, verify_message_signature,
 };
 
 // Payload types - Discovery
 pub use payload::{
-    AnnouncePayload, AnnounceUpdatePayload, SearchFilters, SearchPayload, SearchResponsePayload,
-    SearchResult,
+    AnnouncePayload, AnnounceUpdatePayload, FraudProofPayload, ReportPayload, RevocationPayload,
+    SearchFilters, SearchPayload, SearchResponsePayload, SearchResult, TombstonePayload,
 };
 
 // Payload types - Preview
@@ -129,16 +134,21 @@ pub use payload::{PreviewRequestPayload, PreviewResponsePayload};
 
 // Payload types - Query
 pub use payload::{
-    PaymentReceipt, QueryErrorPayload, QueryRequestPayload, QueryResponsePayload, VersionSpec,
+    BundleItem, ChallengeResponse, ChunkRequestPayload, ChunkResponsePayload, ContentBytes,
+    PaymentReceipt, QueryChallenge, QueryErrorPayload, QueryRequestPayload, QueryResponsePayload,
+    RelayHop, RelayLayer, RelayQueryPayload, RelayResponsePayload, RequestId,
+    UsageReportAckPayload, UsageReportPayload, VersionSpec, MAX_ALTERNATIVE_PROVIDERS,
+    MAX_USAGE_RATING,
 };
 
 // Payload types - Version
-pub use payload::{VersionInfo, VersionRequestPayload, VersionResponsePayload};
+pub use payload::{VersionDelta, VersionInfo, VersionRequestPayload, VersionResponsePayload};
 
 // Payload types - Channel
 pub use payload::{
     ChannelAcceptPayload, ChannelBalances, ChannelCloseAckPayload, ChannelClosePayload,
-    ChannelDisputePayload, ChannelOpenPayload, ChannelUpdatePayload,
+    ChannelDisputePayload, ChannelOpenPayload, ChannelSyncPayload, ChannelSyncResponsePayload,
+    ChannelUpdatePayload, DisputeEvidence,
 };
 
 // Payload types - Settlement
@@ -147,6 +157,32 @@ pub use payload::{SettleBatchPayload, SettleConfirmPayload, SettlementEntry};
 // Payload types - Peer
 pub use payload::{Capability, PeerInfoPayload, PingPayload, PongPayload};
 
+// Payload types - Invoice
+pub use payload::{InvoiceAckPayload, InvoicePayPayload, InvoicePayload, InvoiceRequestPayload};
+
+// Payload types - Group
+pub use payload::{GroupRequestPayload, GroupResponsePayload};
+
+// Payload types - Snapshot
+pub use payload::{SnapshotPeer, SnapshotRequestPayload, SnapshotResponsePayload, StateSnapshot};
+
+// Payload types - Replica catalogs
+pub use payload::{ReplicaCatalog, ReplicaItem};
+
+// Payload types - Storefronts
+pub use payload::{Storefront, StorefrontItem};
+
+// Payload types - Sync
+pub use payload::{
+    EncryptedSyncBundle, SyncPullPayload, SyncPullResponsePayload, SyncPushAckPayload,
+    SyncPushPayload,
+};
+
+// Payload types - Availability
+pub use payload::{
+    AvailabilityCheckPayload, AvailabilityResultPayload, MAX_AVAILABILITY_ADDRESSES,
+};
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -209,6 +245,9 @@ mod tests {
             price: 100,
             addresses: vec!["/ip4/127.0.0.1/tcp/9000".to_string()],
             publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
         };
 
         let enc1 = encode_payload(&payload).unwrap();
@@ -285,6 +324,10 @@ mod tests {
         let types = [
             MessageType::Announce,
             MessageType::AnnounceUpdate,
+            MessageType::Tombstone,
+            MessageType::Report,
+            MessageType::Revocation,
+            MessageType::FraudProof,
             MessageType::Search,
             MessageType::SearchResponse,
             MessageType::PreviewRequest,
@@ -292,6 +335,12 @@ mod tests {
             MessageType::QueryRequest,
             MessageType::QueryResponse,
             MessageType::QueryError,
+            MessageType::UsageReport,
+            MessageType::UsageReportAck,
+            MessageType::RelayQuery,
+            MessageType::RelayResponse,
+            MessageType::ChunkRequest,
+            MessageType::ChunkResponse,
             MessageType::VersionRequest,
             MessageType::VersionResponse,
             MessageType::ChannelOpen,
@@ -300,11 +349,27 @@ mod tests {
             MessageType::ChannelClose,
             MessageType::ChannelDispute,
             MessageType::ChannelCloseAck,
+            MessageType::ChannelSync,
+            MessageType::ChannelSyncResponse,
             MessageType::SettleBatch,
             MessageType::SettleConfirm,
             MessageType::Ping,
             MessageType::Pong,
             MessageType::PeerInfo,
+            MessageType::InvoiceRequest,
+            MessageType::Invoice,
+            MessageType::InvoicePay,
+            MessageType::InvoiceAck,
+            MessageType::GroupRequest,
+            MessageType::GroupResponse,
+            MessageType::SnapshotRequest,
+            MessageType::SnapshotResponse,
+            MessageType::SyncPush,
+            MessageType::SyncPushAck,
+            MessageType::SyncPull,
+            MessageType::SyncPullResponse,
+            MessageType::AvailabilityCheck,
+            MessageType::AvailabilityResult,
         ];
 
         for msg_type in types {
@@ -18,6 +18,10 @@ use crate::error::DecodeError;
 /// - `0x05xx`: Channel messages
 /// - `0x06xx`: Settlement messages
 /// - `0x07xx`: Peer messages
+/// - `0x08xx`: Invoice messages
+/// - `0x09xx`: Group messages
+/// - `0x0Axx`: Snapshot messages
+/// - `0x0Bxx`: Sync messages
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[repr(u16)]
 #[non_exhaustive]
@@ -31,6 +35,18 @@ pub enum MessageType {
     /// Update an existing announcement (new version)
     AnnounceUpdate = 0x0101,
 
+    /// Withdraw content from the network (owner-signed tombstone)
+    Tombstone = 0x0102,
+
+    /// Report content to other nodes' moderation (reporter-signed)
+    Report = 0x0103,
+
+    /// Revoke a compromised identity key (self-signed certificate)
+    Revocation = 0x0104,
+
+    /// Prove a provider served the wrong content (requester-signed)
+    FraudProof = 0x0105,
+
     /// Search for content (hash-based lookup)
     Search = 0x0110,
 
@@ -58,6 +74,24 @@ pub enum MessageType {
     /// Error response to a query
     QueryError = 0x0302,
 
+    /// Anonymized report of how queried content was used (opt-in)
+    UsageReport = 0x0303,
+
+    /// Acknowledgment of a usage report
+    UsageReportAck = 0x0304,
+
+    /// Query routed through relays, one sealed layer per hop
+    RelayQuery = 0x0305,
+
+    /// Reply to a relayed query, encrypted once per hop
+    RelayResponse = 0x0306,
+
+    /// Request one chunk of chunked content
+    ChunkRequest = 0x0307,
+
+    /// Response with the requested chunk
+    ChunkResponse = 0x0308,
+
     // =========================================================================
     // Version Messages (0x04xx)
     // =========================================================================
@@ -88,6 +122,12 @@ pub enum MessageType {
     /// Acknowledge a cooperative channel close (responder's signature)
     ChannelCloseAck = 0x0505,
 
+    /// Request the counterparty's latest signed channel state
+    ChannelSync = 0x0506,
+
+    /// Respond with the latest signed channel state
+    ChannelSyncResponse = 0x0507,
+
     // =========================================================================
     // Settlement Messages (0x06xx)
     // =========================================================================
@@ -108,6 +148,63 @@ pub enum MessageType {
 
     /// Peer information exchange
     PeerInfo = 0x0710,
+
+    // =========================================================================
+    // Invoice Messages (0x08xx)
+    // =========================================================================
+    /// Request an invoice by hash from its payee
+    InvoiceRequest = 0x0800,
+
+    /// A signed invoice (sent to a payer, or in response to a request)
+    Invoice = 0x0801,
+
+    /// Pay an invoice through the payment channel with its payee
+    InvoicePay = 0x0802,
+
+    /// Acknowledge a delivered or paid invoice
+    InvoiceAck = 0x0803,
+
+    // =========================================================================
+    // Group Messages (0x09xx)
+    // =========================================================================
+    /// Request a group membership list from its owner
+    GroupRequest = 0x0900,
+
+    /// The owner's current membership list, if it has one
+    GroupResponse = 0x0901,
+
+    // =========================================================================
+    // Snapshot Messages (0x0Axx)
+    // =========================================================================
+    /// Request a signed snapshot of a peer's announcement index and peer list
+    SnapshotRequest = 0x0A00,
+
+    /// The signed snapshot, if the peer serves them
+    SnapshotResponse = 0x0A01,
+
+    // =========================================================================
+    // Sync Messages (0x0Bxx)
+    // =========================================================================
+    /// Leave an encrypted sync bundle for the owner's other devices
+    SyncPush = 0x0B00,
+
+    /// Whether the peer stored the bundle
+    SyncPushAck = 0x0B01,
+
+    /// Fetch the sync bundle held for an owner
+    SyncPull = 0x0B02,
+
+    /// The bundle held, if any
+    SyncPullResponse = 0x0B03,
+
+    // =========================================================================
+    // Availability Messages (0x0Cxx)
+    // =========================================================================
+    /// Ask a peer to check that the sender's content is reachable
+    AvailabilityCheck = 0x0C00,
+
+    /// Whether the peer could dial the sender and preview the content
+    AvailabilityResult = 0x0C01,
 }
 
 impl MessageType {
@@ -119,6 +216,10 @@ impl MessageType {
             // Discovery
             0x0100 => Ok(MessageType::Announce),
             0x0101 => Ok(MessageType::AnnounceUpdate),
+            0x0102 => Ok(MessageType::Tombstone),
+            0x0103 => Ok(MessageType::Report),
+            0x0104 => Ok(MessageType::Revocation),
+            0x0105 => Ok(MessageType::FraudProof),
             0x0110 => Ok(MessageType::Search),
             0x0111 => Ok(MessageType::SearchResponse),
             // Preview
@@ -128,6 +229,12 @@ impl MessageType {
             0x0300 => Ok(MessageType::QueryRequest),
             0x0301 => Ok(MessageType::QueryResponse),
             0x0302 => Ok(MessageType::QueryError),
+            0x0303 => Ok(MessageType::UsageReport),
+            0x0304 => Ok(MessageType::UsageReportAck),
+            0x0305 => Ok(MessageType::RelayQuery),
+            0x0306 => Ok(MessageType::RelayResponse),
+            0x0307 => Ok(MessageType::ChunkRequest),
+            0x0308 => Ok(MessageType::ChunkResponse),
             // Version
             0x0400 => Ok(MessageType::VersionRequest),
             0x0401 => Ok(MessageType::VersionResponse),
@@ -138,6 +245,8 @@ impl MessageType {
             0x0503 => Ok(MessageType::ChannelClose),
             0x0504 => Ok(MessageType::ChannelDispute),
             0x0505 => Ok(MessageType::ChannelCloseAck),
+            0x0506 => Ok(MessageType::ChannelSync),
+            0x0507 => Ok(MessageType::ChannelSyncResponse),
             // Settlement
             0x0600 => Ok(MessageType::SettleBatch),
             0x0601 => Ok(MessageType::SettleConfirm),
@@ -145,6 +254,25 @@ impl MessageType {
             0x0700 => Ok(MessageType::Ping),
             0x0701 => Ok(MessageType::Pong),
             0x0710 => Ok(MessageType::PeerInfo),
+            // Invoice
+            0x0800 => Ok(MessageType::InvoiceRequest),
+            0x0801 => Ok(MessageType::Invoice),
+            0x0802 => Ok(MessageType::InvoicePay),
+            0x0803 => Ok(MessageType::InvoiceAck),
+            // Group
+            0x0900 => Ok(MessageType::GroupRequest),
+            0x0901 => Ok(MessageType::GroupResponse),
+            // Snapshot
+            0x0A00 => Ok(MessageType::SnapshotRequest),
+            0x0A01 => Ok(MessageType::SnapshotResponse),
+            // Sync
+            0x0B00 => Ok(MessageType::SyncPush),
+            0x0B01 => Ok(MessageType::SyncPushAck),
+            0x0B02 => Ok(MessageType::SyncPull),
+            0x0B03 => Ok(MessageType::SyncPullResponse),
+            // Availability
+            0x0C00 => Ok(MessageType::AvailabilityCheck),
+            0x0C01 => Ok(MessageType::AvailabilityResult),
             _ => Err(DecodeError::InvalidMessageType(value)),
         }
     }
@@ -196,6 +324,36 @@ impl MessageType {
         (0x0700..=0x07FF).contains(&code)
     }
 
+    /// Check if this is an invoice message (0x08xx).
+    pub fn is_invoice(&self) -> bool {
+        let code = *self as u16;
+        (0x0800..=0x08FF).contains(&code)
+    }
+
+    /// Check if this is a group message (0x09xx).
+    pub fn is_group(&self) -> bool {
+        let code = *self as u16;
+        (0x0900..=0x09FF).contains(&code)
+    }
+
+    /// Check if this is a snapshot message (0x0Axx).
+    pub fn is_snapshot(&self) -> bool {
+        let code = *self as u16;
+        (0x0A00..=0x0AFF).contains(&code)
+    }
+
+    /// Check if this is a sync message (0x0Bxx).
+    pub fn is_sync(&self) -> bool {
+        let code = *self as u16;
+        (0x0B00..=0x0BFF).contains(&code)
+    }
+
+    /// Check if this is an availability message (0x0Cxx).
+    pub fn is_availability(&self) -> bool {
+        let code = *self as u16;
+        (0x0C00..=0x0CFF).contains(&code)
+    }
+
     /// Check if this message type expects a response.
     pub fn expects_response(&self) -> bool {
         matches!(
@@ -203,9 +361,19 @@ impl MessageType {
             MessageType::Search
                 | MessageType::PreviewRequest
                 | MessageType::QueryRequest
+                | MessageType::UsageReport
+                | MessageType::RelayQuery
+                | MessageType::ChunkRequest
                 | MessageType::VersionRequest
                 | MessageType::ChannelOpen
                 | MessageType::Ping
+                | MessageType::InvoiceRequest
+                | MessageType::InvoicePay
+                | MessageType::GroupRequest
+                | MessageType::SnapshotRequest
+                | MessageType::SyncPush
+                | MessageType::SyncPull
+                | MessageType::AvailabilityCheck
         )
     }
 }
@@ -215,6 +383,10 @@ impl std::fmt::Display for MessageType {
         match self {
             MessageType::Announce => write!(f, "ANNOUNCE"),
             MessageType::AnnounceUpdate => write!(f, "ANNOUNCE_UPDATE"),
+            MessageType::Tombstone => write!(f, "TOMBSTONE"),
+            MessageType::Report => write!(f, "REPORT"),
+            MessageType::Revocation => write!(f, "REVOCATION"),
+            MessageType::FraudProof => write!(f, "FRAUD_PROOF"),
             MessageType::Search => write!(f, "SEARCH"),
             MessageType::SearchResponse => write!(f, "SEARCH_RESPONSE"),
             MessageType::PreviewRequest => write!(f, "PREVIEW_REQUEST"),
@@ -222,6 +394,12 @@ impl std::fmt::Display for MessageType {
             MessageType::QueryRequest => write!(f, "QUERY_REQUEST"),
             MessageType::QueryResponse => write!(f, "QUERY_RESPONSE"),
             MessageType::QueryError => write!(f, "QUERY_ERROR"),
+            MessageType::UsageReport => write!(f, "USAGE_REPORT"),
+            MessageType::UsageReportAck => write!(f, "USAGE_REPORT_ACK"),
+            MessageType::RelayQuery => write!(f, "RELAY_QUERY"),
+            MessageType::RelayResponse => write!(f, "RELAY_RESPONSE"),
+            MessageType::ChunkRequest => write!(f, "CHUNK_REQUEST"),
+            MessageType::ChunkResponse => write!(f, "CHUNK_RESPONSE"),
             MessageType::VersionRequest => write!(f, "VERSION_REQUEST"),
             MessageType::VersionResponse => write!(f, "VERSION_RESPONSE"),
             MessageType::ChannelOpen => write!(f, "CHANNEL_OPEN"),
@@ -230,11 +408,27 @@ impl std::fmt::Display for MessageType {
             MessageType::ChannelClose => write!(f, "CHANNEL_CLOSE"),
             MessageType::ChannelDispute => write!(f, "CHANNEL_DISPUTE"),
             MessageType::ChannelCloseAck => write!(f, "CHANNEL_CLOSE_ACK"),
+            MessageType::ChannelSync => write!(f, "CHANNEL_SYNC"),
+            MessageType::ChannelSyncResponse => write!(f, "CHANNEL_SYNC_RESPONSE"),
             MessageType::SettleBatch => write!(f, "SETTLE_BATCH"),
             MessageType::SettleConfirm => write!(f, "SETTLE_CONFIRM"),
             MessageType::Ping => write!(f, "PING"),
             MessageType::Pong => write!(f, "PONG"),
             MessageType::PeerInfo => write!(f, "PEER_INFO"),
+            MessageType::InvoiceRequest => write!(f, "INVOICE_REQUEST"),
+            MessageType::Invoice => write!(f, "INVOICE"),
+            MessageType::InvoicePay => write!(f, "INVOICE_PAY"),
+            MessageType::InvoiceAck => write!(f, "INVOICE_ACK"),
+            MessageType::GroupRequest => write!(f, "GROUP_REQUEST"),
+            MessageType::GroupResponse => write!(f, "GROUP_RESPONSE"),
+            MessageType::SnapshotRequest => write!(f, "SNAPSHOT_REQUEST"),
+            MessageType::SnapshotResponse => write!(f, "SNAPSHOT_RESPONSE"),
+            MessageType::SyncPush => write!(f, "SYNC_PUSH"),
+            MessageType::SyncPushAck => write!(f, "SYNC_PUSH_ACK"),
+            MessageType::SyncPull => write!(f, "SYNC_PULL"),
+            MessageType::SyncPullResponse => write!(f, "SYNC_PULL_RESPONSE"),
+            MessageType::AvailabilityCheck => write!(f, "AVAILABILITY_CHECK"),
+            MessageType::AvailabilityResult => write!(f, "AVAILABILITY_RESULT"),
         }
     }
 }
@@ -311,6 +505,10 @@ mod tests {
         // Discovery
         assert_eq!(MessageType::Announce as u16, 0x0100);
         assert_eq!(MessageType::AnnounceUpdate as u16, 0x0101);
+        assert_eq!(MessageType::Tombstone as u16, 0x0102);
+        assert_eq!(MessageType::Report as u16, 0x0103);
+        assert_eq!(MessageType::Revocation as u16, 0x0104);
+        assert_eq!(MessageType::FraudProof as u16, 0x0105);
         assert_eq!(MessageType::Search as u16, 0x0110);
         assert_eq!(MessageType::SearchResponse as u16, 0x0111);
 
@@ -322,6 +520,12 @@ mod tests {
         assert_eq!(MessageType::QueryRequest as u16, 0x0300);
         assert_eq!(MessageType::QueryResponse as u16, 0x0301);
         assert_eq!(MessageType::QueryError as u16, 0x0302);
+        assert_eq!(MessageType::UsageReport as u16, 0x0303);
+        assert_eq!(MessageType::UsageReportAck as u16, 0x0304);
+        assert_eq!(MessageType::RelayQuery as u16, 0x0305);
+        assert_eq!(MessageType::RelayResponse as u16, 0x0306);
+        assert_eq!(MessageType::ChunkRequest as u16, 0x0307);
+        assert_eq!(MessageType::ChunkResponse as u16, 0x0308);
 
         // Version
         assert_eq!(MessageType::VersionRequest as u16, 0x0400);
@@ -342,6 +546,30 @@ mod tests {
         assert_eq!(MessageType::Ping as u16, 0x0700);
         assert_eq!(MessageType::Pong as u16, 0x0701);
         assert_eq!(MessageType::PeerInfo as u16, 0x0710);
+
+        // Invoice
+        assert_eq!(MessageType::InvoiceRequest as u16, 0x0800);
+        assert_eq!(MessageType::Invoice as u16, 0x0801);
+        assert_eq!(MessageType::InvoicePay as u16, 0x0802);
+        assert_eq!(MessageType::InvoiceAck as u16, 0x0803);
+
+        // Group
+        assert_eq!(MessageType::GroupRequest as u16, 0x0900);
+        assert_eq!(MessageType::GroupResponse as u16, 0x0901);
+
+        // Snapshot
+        assert_eq!(MessageType::SnapshotRequest as u16, 0x0A00);
+        assert_eq!(MessageType::SnapshotResponse as u16, 0x0A01);
+
+        // Sync
+        assert_eq!(MessageType::SyncPush as u16, 0x0B00);
+        assert_eq!(MessageType::SyncPushAck as u16, 0x0B01);
+        assert_eq!(MessageType::SyncPull as u16, 0x0B02);
+        assert_eq!(MessageType::SyncPullResponse as u16, 0x0B03);
+
+        // Availability
+        assert_eq!(MessageType::AvailabilityCheck as u16, 0x0C00);
+        assert_eq!(MessageType::AvailabilityResult as u16, 0x0C01);
     }
 
     #[test]
@@ -364,6 +592,10 @@ mod tests {
     fn test_message_type_categories() {
         assert!(MessageType::Announce.is_discovery());
         assert!(MessageType::Search.is_discovery());
+        assert!(MessageType::Tombstone.is_discovery());
+        assert!(MessageType::Report.is_discovery());
+        assert!(MessageType::Revocation.is_discovery());
+        assert!(MessageType::FraudProof.is_discovery());
         assert!(!MessageType::Announce.is_query());
 
         assert!(MessageType::PreviewRequest.is_preview());
@@ -371,6 +603,12 @@ mod tests {
 
         assert!(MessageType::QueryRequest.is_query());
         assert!(MessageType::QueryError.is_query());
+        assert!(MessageType::UsageReport.is_query());
+        assert!(MessageType::UsageReportAck.is_query());
+        assert!(MessageType::RelayQuery.is_query());
+        assert!(MessageType::RelayResponse.is_query());
+        assert!(MessageType::ChunkRequest.is_query());
+        assert!(MessageType::ChunkResponse.is_query());
 
         assert!(MessageType::VersionRequest.is_version());
         assert!(MessageType::VersionResponse.is_version());
@@ -383,6 +621,26 @@ mod tests {
 
         assert!(MessageType::Ping.is_peer());
         assert!(MessageType::PeerInfo.is_peer());
+
+        assert!(MessageType::InvoiceRequest.is_invoice());
+        assert!(MessageType::InvoiceAck.is_invoice());
+        assert!(!MessageType::PeerInfo.is_invoice());
+
+        assert!(MessageType::GroupRequest.is_group());
+        assert!(MessageType::GroupResponse.is_group());
+        assert!(!MessageType::InvoiceAck.is_group());
+
+        assert!(MessageType::SnapshotRequest.is_snapshot());
+        assert!(MessageType::SnapshotResponse.is_snapshot());
+        assert!(!MessageType::GroupResponse.is_snapshot());
+
+        assert!(MessageType::SyncPush.is_sync());
+        assert!(MessageType::SyncPullResponse.is_sync());
+        assert!(!MessageType::SnapshotResponse.is_sync());
+
+        assert!(MessageType::AvailabilityCheck.is_availability());
+        assert!(MessageType::AvailabilityResult.is_availability());
+        assert!(!MessageType::SyncPullResponse.is_availability());
     }
 
     #[test]
@@ -393,6 +651,13 @@ mod tests {
         assert!(MessageType::VersionRequest.expects_response());
         assert!(MessageType::ChannelOpen.expects_response());
         assert!(MessageType::Ping.expects_response());
+        assert!(MessageType::RelayQuery.expects_response());
+        assert!(MessageType::ChunkRequest.expects_response());
+        assert!(MessageType::SyncPush.expects_response());
+        assert!(MessageType::SyncPull.expects_response());
+        assert!(!MessageType::SyncPushAck.expects_response());
+        assert!(MessageType::AvailabilityCheck.expects_response());
+        assert!(!MessageType::AvailabilityResult.expects_response());
 
         assert!(!MessageType::SearchResponse.expects_response());
         assert!(!MessageType::Announce.expects_response());
@@ -429,6 +694,10 @@ mod tests {
         let all_types = [
             (0x0100u16, MessageType::Announce),
             (0x0101, MessageType::AnnounceUpdate),
+            (0x0102, MessageType::Tombstone),
+            (0x0103, MessageType::Report),
+            (0x0104, MessageType::Revocation),
+            (0x0105, MessageType::FraudProof),
             (0x0110, MessageType::Search),
             (0x0111, MessageType::SearchResponse),
             (0x0200, MessageType::PreviewRequest),
@@ -436,6 +705,12 @@ mod tests {
             (0x0300, MessageType::QueryRequest),
             (0x0301, MessageType::QueryResponse),
             (0x0302, MessageType::QueryError),
+            (0x0303, MessageType::UsageReport),
+            (0x0304, MessageType::UsageReportAck),
+            (0x0305, MessageType::RelayQuery),
+            (0x0306, MessageType::RelayResponse),
+            (0x0307, MessageType::ChunkRequest),
+            (0x0308, MessageType::ChunkResponse),
             (0x0400, MessageType::VersionRequest),
             (0x0401, MessageType::VersionResponse),
             (0x0500, MessageType::ChannelOpen),
@@ -444,11 +719,27 @@ mod tests {
             (0x0503, MessageType::ChannelClose),
             (0x0504, MessageType::ChannelDispute),
             (0x0505, MessageType::ChannelCloseAck),
+            (0x0506, MessageType::ChannelSync),
+            (0x0507, MessageType::ChannelSyncResponse),
             (0x0600, MessageType::SettleBatch),
             (0x0601, MessageType::SettleConfirm),
             (0x0700, MessageType::Ping),
             (0x0701, MessageType::Pong),
             (0x0710, MessageType::PeerInfo),
+            (0x0800, MessageType::InvoiceRequest),
+            (0x0801, MessageType::Invoice),
+            (0x0802, MessageType::InvoicePay),
+            (0x0803, MessageType::InvoiceAck),
+            (0x0900, MessageType::GroupRequest),
+            (0x0901, MessageType::GroupResponse),
+            (0x0A00, MessageType::SnapshotRequest),
+            (0x0A01, MessageType::SnapshotResponse),
+            (0x0B00, MessageType::SyncPush),
+            (0x0B01, MessageType::SyncPushAck),
+            (0x0B02, MessageType::SyncPull),
+            (0x0B03, MessageType::SyncPullResponse),
+            (0x0C00, MessageType::AvailabilityCheck),
+            (0x0C01, MessageType::AvailabilityResult),
         ];
         for (value, expected) in all_types {
             let parsed = MessageType::from_u16(value).unwrap();
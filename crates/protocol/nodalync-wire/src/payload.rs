@@ -3,9 +3,16 @@
 //! This module defines the type-specific payloads for each message type
 //! as specified in Protocol Specification §6.2-§6.8.
 
-use nodalync_crypto::{Hash, PeerId, PublicKey, Signature, Timestamp};
-use nodalync_types::{Amount, ContentType, ErrorCode, L1Summary, Manifest, Payment, Visibility};
-use serde::{Deserialize, Serialize};
+use nodalync_crypto::{Hash, PeerId, PublicKey, SealedData, Signature, Timestamp, WrappedKey};
+use nodalync_types::{
+    Amount, CanonicalVersion, CapabilityToken, Collection, ContentReport, ContentType,
+    DeliveryCommitment, ErrorCode, FraudProof, Group, Invoice, L1Summary, Manifest, Payment,
+    ReplicaDelegation, RevocationCertificate, Tombstone, Visibility,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
 
 // =============================================================================
 // Discovery Payloads (§6.2)
@@ -33,6 +40,16 @@ pub struct AnnouncePayload {
     /// Used to dial the publisher directly when retrieving content
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub publisher_peer_id: Option<String>,
+    /// The publisher's identity key, which `signature` verifies against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publisher_key: Option<PublicKey>,
+    /// The publisher's signature over all other fields
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<Signature>,
+    /// Set when a replica announces its primary's content: the primary's
+    /// delegation naming the replica, whose key signs the announcement
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delegation: Option<ReplicaDelegation>,
 }
 
 /// Payload for ANNOUNCE_UPDATE messages.
@@ -55,6 +72,52 @@ pub struct AnnounceUpdatePayload {
     pub price: Amount,
 }
 
+/// Payload for TOMBSTONE messages.
+///
+/// Withdraws content from the network. Peers drop cached copies, stop
+/// listing the announcement, and refuse to serve the hash, provided the
+/// tombstone is signed by the content owner.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct TombstonePayload {
+    /// The owner-signed tombstone
+    pub tombstone: Tombstone,
+}
+
+/// Payload for REPORT messages.
+///
+/// Reports content to other nodes. Each node applies its own moderation
+/// policy; a report only counts if signed by the reporter it names.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ReportPayload {
+    /// The reporter-signed report
+    pub report: ContentReport,
+}
+
+/// Payload for REVOCATION messages.
+///
+/// Revokes a compromised identity key. Peers stop accepting messages and
+/// manifests from it, provided the certificate is signed by the revoked
+/// key. Anyone holding the certificate can broadcast it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RevocationPayload {
+    /// The self-signed revocation certificate
+    pub certificate: RevocationCertificate,
+}
+
+/// Payload for FRAUD_PROOF messages.
+///
+/// Proves that a provider served bytes not matching the content it was
+/// paid for. Peers that accept the proof lower the provider's reputation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct FraudProofPayload {
+    /// The requester-signed fraud proof
+    pub proof: FraudProof,
+}
+
 /// Payload for SEARCH messages.
 ///
 /// Requests content by hash lookup in the DHT.
@@ -72,10 +135,13 @@ pub struct SearchPayload {
 }
 
 /// Filters for search queries.
+///
+/// All set filters must match. `min_reputation` is reserved and not yet
+/// applied by responders.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub struct SearchFilters {
-    /// Filter by content types
+    /// Filter by content types (any of)
     pub content_types: Option<Vec<ContentType>>,
     /// Maximum price filter
     pub max_price: Option<Amount>,
@@ -85,8 +151,35 @@ pub struct SearchFilters {
     pub created_after: Option<Timestamp>,
     /// Created before timestamp
     pub created_before: Option<Timestamp>,
-    /// Filter by tags
+    /// Filter by tags (all of, case-insensitive; a tag also matches its
+    /// descendants)
     pub tags: Option<Vec<String>>,
+    /// Minimum price filter
+    #[serde(default)]
+    pub min_price: Option<Amount>,
+    /// Filter by content owner
+    #[serde(default)]
+    pub owner: Option<PeerId>,
+}
+
+impl SearchFilters {
+    /// Whether no filter is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check the filters a search result carries enough data for.
+    ///
+    /// Tags and creation time aren't part of a result, so they are left to
+    /// the responder.
+    pub fn matches_result(&self, result: &SearchResult) -> bool {
+        self.content_types
+            .as_ref()
+            .is_none_or(|types| types.contains(&result.content_type))
+            && self.min_price.is_none_or(|min| result.price >= min)
+            && self.max_price.is_none_or(|max| result.price <= max)
+            && self.owner.is_none_or(|owner| result.owner == owner)
+    }
 }
 
 /// Payload for SEARCH_RESPONSE messages.
@@ -149,15 +242,55 @@ pub struct PreviewResponsePayload {
     pub manifest: Manifest,
     /// L1 summary with preview mentions
     pub l1_summary: L1Summary,
+    /// Item list, when the content is a collection
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<Collection>,
+    /// Other versions the owner published with the same version number
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forks: Vec<Hash>,
+    /// The owner's canonical pointer for the lineage, if one is held
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonical: Option<CanonicalVersion>,
+    /// Paid queries in the surge pricing window, so the requester can
+    /// compute the current price from the manifest's pricing rule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_queries: Option<u64>,
 }
 
 // =============================================================================
 // Query Payloads (§6.4)
 // =============================================================================
 
+/// Correlation ID carried by a request, so the requester's and the
+/// provider's traces of one query can be joined up.
+///
+/// Shown as 16 hex digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RequestId(pub u64);
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl std::str::FromStr for RequestId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u64::from_str_radix(s, 16).map(Self)
+    }
+}
+
 /// Payload for QUERY_REQUEST messages.
 ///
-/// Requests full content with payment.
+/// Requests full content with payment. Free content (price 0) is queried
+/// without a payment, which skips channel and settlement handling, as is
+/// content bought earlier when the purchase receipt is presented. A
+/// capability token from the owner grants access to unpublished content.
+/// Providers may first answer with a [`QueryChallenge`], which the request
+/// is retried with a signed [`ChallengeResponse`] to.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct QueryRequestPayload {
@@ -165,13 +298,69 @@ pub struct QueryRequestPayload {
     pub hash: Hash,
     /// Optional natural language query (for future use)
     pub query: Option<String>,
-    /// Payment for this query
-    pub payment: Payment,
+    /// Payment for this query (absent for free content)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payment: Option<Payment>,
     /// Optional version specification
     pub version_spec: Option<VersionSpec>,
     /// Payment nonce for replay protection (must be > channel nonce)
     #[serde(default)]
     pub payment_nonce: u64,
+    /// Owner-signed token granting access regardless of visibility
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capability: Option<CapabilityToken>,
+    /// Requester's public key, to which restricted content keys are wrapped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipient_key: Option<PublicKey>,
+    /// Answer to the provider's challenge, when it issued one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub challenge_response: Option<ChallengeResponse>,
+    /// Requester's correlation ID for tracing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<RequestId>,
+    /// Index of the manifest section to buy, instead of the whole content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<u32>,
+    /// Index of the metered unit to buy, when reading metered content unit
+    /// by unit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<u32>,
+    /// Discovery node that referred this query, to be paid the content's
+    /// referral cut
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub referrer: Option<PeerId>,
+    /// Receipt from an earlier paid query of this content by the same
+    /// requester, to be served it again free within the provider's
+    /// entitlement window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entitlement: Option<PaymentReceipt>,
+}
+
+/// Proof-of-possession challenge a provider issues before serving a query.
+///
+/// Sent in a QUERY_ERROR with `ChallengeRequired`. The nonce is good for
+/// one request, so a captured request can't be replayed by a third party.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct QueryChallenge {
+    /// Random single-use nonce
+    pub nonce: Hash,
+    /// Provider that issued the challenge
+    pub provider: PeerId,
+    /// When the challenge stops being accepted
+    pub expires_at: Timestamp,
+}
+
+/// A requester's signed answer to a [`QueryChallenge`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ChallengeResponse {
+    /// Nonce from the challenge
+    pub nonce: Hash,
+    /// Requester's identity key
+    pub public_key: PublicKey,
+    /// Signature over the challenge, content hash, provider and requester
+    pub signature: Signature,
 }
 
 /// Specification for which version to retrieve.
@@ -187,18 +376,166 @@ pub enum VersionSpec {
     Hash(Hash),
 }
 
+/// Content bytes carried in a query response.
+///
+/// Either owned, or shared with whatever holds them (such as a
+/// memory-mapped file), so large content is encoded for the wire straight
+/// from where it is stored. Encodes exactly like `Vec<u8>`; decoding
+/// always yields owned bytes.
+#[derive(Clone)]
+pub struct ContentBytes(ContentBytesInner);
+
+#[derive(Clone)]
+enum ContentBytesInner {
+    Owned(Vec<u8>),
+    Shared(Arc<dyn AsRef<[u8]> + Send + Sync>),
+}
+
+impl ContentBytes {
+    /// Wrap bytes owned elsewhere without copying them.
+    pub fn shared(bytes: impl AsRef<[u8]> + Send + Sync + 'static) -> Self {
+        Self(ContentBytesInner::Shared(Arc::new(bytes)))
+    }
+
+    /// The content bytes.
+    pub fn as_slice(&self) -> &[u8] {
+        match &self.0 {
+            ContentBytesInner::Owned(bytes) => bytes,
+            ContentBytesInner::Shared(bytes) => bytes.as_ref().as_ref(),
+        }
+    }
+
+    /// Whether the bytes are shared rather than owned.
+    pub fn is_shared(&self) -> bool {
+        matches!(self.0, ContentBytesInner::Shared(_))
+    }
+
+    /// Take the bytes as a vector, copying them if they are shared.
+    pub fn into_vec(self) -> Vec<u8> {
+        match self.0 {
+            ContentBytesInner::Owned(bytes) => bytes,
+            ContentBytesInner::Shared(bytes) => bytes.as_ref().as_ref().to_vec(),
+        }
+    }
+}
+
+impl Default for ContentBytes {
+    fn default() -> Self {
+        Self(ContentBytesInner::Owned(Vec::new()))
+    }
+}
+
+impl Deref for ContentBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for ContentBytes {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl From<Vec<u8>> for ContentBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(ContentBytesInner::Owned(bytes))
+    }
+}
+
+impl From<&[u8]> for ContentBytes {
+    fn from(bytes: &[u8]) -> Self {
+        Self(ContentBytesInner::Owned(bytes.to_vec()))
+    }
+}
+
+impl fmt::Debug for ContentBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl PartialEq for ContentBytes {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for ContentBytes {}
+
+impl PartialEq<[u8]> for ContentBytes {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl PartialEq<Vec<u8>> for ContentBytes {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Serialize for ContentBytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Same encoding as `Vec<u8>`
+        serializer.collect_seq(self.as_slice())
+    }
+}
+
+impl<'de> Deserialize<'de> for ContentBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<u8>::deserialize(deserializer).map(Self::from)
+    }
+}
+
 /// Payload for QUERY_RESPONSE messages.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct QueryResponsePayload {
     /// Content hash
     pub hash: Hash,
-    /// Full content bytes
-    pub content: Vec<u8>,
+    /// Full content bytes (ciphertext when `content_key` is set)
+    pub content: ContentBytes,
     /// Content manifest
     pub manifest: Manifest,
     /// Payment receipt
     pub payment_receipt: PaymentReceipt,
+    /// Content key wrapped to the requester, when the content is encrypted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_key: Option<WrappedKey>,
+    /// Item contents, when the content is a collection sold as a bundle
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bundle: Vec<BundleItem>,
+    /// Provider's signed commitment to the content bytes, when they are
+    /// sent in plaintext
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delivery: Option<DeliveryCommitment>,
+    /// Index of the section `content` holds, when only one was bought
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<u32>,
+    /// Index of the metered unit `content` holds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<u32>,
+    /// Free trial reads the requester has left, when this was one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trial_reads_left: Option<u32>,
+}
+
+/// One item delivered with a paid collection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct BundleItem {
+    /// Item content hash
+    pub hash: Hash,
+    /// Item manifest
+    pub manifest: Manifest,
+    /// Full item content bytes (ciphertext when `content_key` is set)
+    pub content: Vec<u8>,
+    /// Item key wrapped to the requester, when the item is encrypted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_key: Option<WrappedKey>,
 }
 
 /// Receipt confirming payment was processed.
@@ -215,6 +552,18 @@ pub struct PaymentReceipt {
     pub channel_nonce: u64,
     /// Signature from content distributor
     pub distributor_signature: Signature,
+    /// Platform fee taken by the serving app (included in `amount`)
+    #[serde(default)]
+    pub app_fee: Amount,
+    /// Recipient of the platform fee, if one was taken
+    #[serde(default)]
+    pub app_fee_recipient: Option<PeerId>,
+    /// Referral cut paid to the referrer (included in `amount`)
+    #[serde(default)]
+    pub referral_fee: Amount,
+    /// Referrer paid the referral cut, if one was
+    #[serde(default)]
+    pub referrer: Option<PeerId>,
 }
 
 /// Payload for QUERY_ERROR messages.
@@ -235,6 +584,167 @@ pub struct QueryErrorPayload {
     /// for direct connection. Base58 encoded.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub required_channel_libp2p_peer: Option<String>,
+    /// When error_code is ChallengeRequired, the challenge to answer before
+    /// retrying.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub challenge: Option<QueryChallenge>,
+    /// For temporary errors, how long to wait before retrying, in
+    /// milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
+    /// Other providers that may serve the content, as base58 libp2p peer
+    /// IDs (at most [`MAX_ALTERNATIVE_PROVIDERS`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternative_providers: Vec<String>,
+}
+
+/// Most alternative providers a QUERY_ERROR lists.
+pub const MAX_ALTERNATIVE_PROVIDERS: usize = 8;
+
+impl QueryErrorPayload {
+    /// An error for `hash` with only a code and message.
+    pub fn new(hash: Hash, error_code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            hash,
+            error_code,
+            message: Some(message.into()),
+            required_channel_peer_id: None,
+            required_channel_libp2p_peer: None,
+            challenge: None,
+            retry_after_ms: None,
+            alternative_providers: Vec::new(),
+        }
+    }
+
+    /// Numeric value of the error code (spec Appendix C).
+    pub fn code(&self) -> u16 {
+        self.error_code.code()
+    }
+
+    /// Whether the query may succeed if sent again, after any
+    /// `retry_after_ms` or answering the challenge.
+    pub fn is_retryable(&self) -> bool {
+        self.error_code.is_retryable() || self.retry_after_ms.is_some()
+    }
+}
+
+/// Highest outcome rating a usage report may carry (ratings are 1-5).
+pub const MAX_USAGE_RATING: u8 = 5;
+
+/// Payload for USAGE_REPORT messages.
+///
+/// Sent by a consumer after using queried content, if it has opted in.
+/// It carries no consumer identity beyond the message envelope, and the
+/// publisher stores only the aggregate figures.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct UsageReportPayload {
+    /// Content hash that was used
+    pub hash: Hash,
+    /// Bytes of the content that were actually read
+    pub bytes_read: u64,
+    /// Size of the model context the content was loaded into, in tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_tokens: Option<u32>,
+    /// Outcome rating from 1 (useless) to [`MAX_USAGE_RATING`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<u8>,
+}
+
+/// Payload for USAGE_REPORT_ACK messages.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct UsageReportAckPayload {
+    /// Content hash the report was about
+    pub hash: Hash,
+    /// Whether the report was recorded
+    pub accepted: bool,
+    /// Why it was refused
+    pub reason: Option<String>,
+}
+
+/// Payload for RELAY_QUERY messages.
+///
+/// Carries one layer of a query routed through relays. Only the relay the
+/// layer is sealed to can open it, and it learns nothing beyond the next
+/// hop. The payment covers the relay's fee and whatever it passes on, and
+/// its query hash is the hash of the layer's ciphertext.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RelayQueryPayload {
+    /// This hop's [`RelayLayer`], CBOR-encoded and sealed to the relay
+    pub layer: SealedData,
+    /// Payment to the relay through the channel with the previous hop
+    pub payment: Payment,
+    /// Payment nonce for replay protection (must be > channel nonce)
+    pub payment_nonce: u64,
+}
+
+/// One layer of a relayed query, as opened by its relay.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RelayLayer {
+    /// Key the relay encrypts the reply with before passing it back
+    pub reply_key: [u8; 32],
+    /// Where the query goes next
+    pub next: RelayHop,
+}
+
+/// What a relay does with a query once it has opened its layer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayHop {
+    /// Pass the inner layer on to another relay
+    Relay {
+        /// Next relay
+        peer: PeerId,
+        /// The next relay's layer, sealed to it
+        layer: SealedData,
+        /// Amount to pay the next relay
+        amount: Amount,
+    },
+    /// Query the content as the exit and reply with it
+    Exit {
+        /// Content hash to query
+        hash: Hash,
+        /// Most the exit may pay the provider
+        max_price: Amount,
+    },
+}
+
+/// Payload for RELAY_RESPONSE messages.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RelayResponsePayload {
+    /// CBOR-encoded [`QueryResponsePayload`] from the exit, encrypted with
+    /// each relay's reply key in turn
+    pub data: Vec<u8>,
+}
+
+/// Payload for CHUNK_REQUEST messages.
+///
+/// Asks a provider for one chunk of content whose manifest carries a chunk
+/// tree, so a download can be spread across providers and a corrupt chunk
+/// re-fetched on its own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ChunkRequestPayload {
+    /// Content hash
+    pub hash: Hash,
+    /// Zero-based chunk index
+    pub index: u32,
+}
+
+/// Payload for CHUNK_RESPONSE messages.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ChunkResponsePayload {
+    /// Content hash
+    pub hash: Hash,
+    /// Zero-based chunk index
+    pub index: u32,
+    /// Chunk bytes, checked by the requester against the manifest's chunk tree
+    pub data: Vec<u8>,
 }
 
 // =============================================================================
@@ -247,6 +757,14 @@ pub struct QueryErrorPayload {
 pub struct VersionRequestPayload {
     /// Stable version root identifier
     pub version_root: Hash,
+    /// Version the requester already holds; asks for a delta from it to
+    /// the latest version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta_from: Option<Hash>,
+    /// Asks for the manifests of the versions, to repair gaps in the
+    /// requester's copy of the chain
+    #[serde(default)]
+    pub include_manifests: bool,
 }
 
 /// Payload for VERSION_RESPONSE messages.
@@ -259,6 +777,31 @@ pub struct VersionResponsePayload {
     pub versions: Vec<VersionInfo>,
     /// Hash of the latest version
     pub latest: Hash,
+    /// Delta to the latest version, if one was requested and is available
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta: Option<VersionDelta>,
+    /// Manifests of the versions, if requested; only versions the
+    /// requester may access are included
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub manifests: Vec<Manifest>,
+    /// The owner's canonical pointer for the lineage, if one is held
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonical: Option<CanonicalVersion>,
+}
+
+/// Delta between two versions of the same content.
+///
+/// Applying `delta` to the content of `base` yields the content described
+/// by `manifest`; the result must be verified against `manifest.hash`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct VersionDelta {
+    /// Version the delta applies to
+    pub base: Hash,
+    /// Manifest of the resulting version
+    pub manifest: Manifest,
+    /// Encoded delta bytes
+    pub delta: Vec<u8>,
 }
 
 /// Information about a single version.
@@ -399,10 +942,61 @@ pub struct ChannelDisputePayload {
     pub channel_id: Hash,
     /// Highest known channel state
     pub claimed_state: ChannelUpdatePayload,
-    /// Supporting evidence (signed states, etc.)
+    /// Supporting evidence, each item an encoded [`DisputeEvidence`]
     pub evidence: Vec<Vec<u8>>,
 }
 
+/// An item of supporting evidence in a CHANNEL_DISPUTE.
+///
+/// Each item is CBOR-encoded into [`ChannelDisputePayload::evidence`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeEvidence {
+    /// An earlier signed state of the channel
+    State(ChannelUpdatePayload),
+    /// A payment on the channel, signed by its payer
+    Payment(Payment),
+    /// A receipt the counterparty signed for a query we paid for
+    Receipt(PaymentReceipt),
+    /// A fraud proof against the counterparty
+    FraudProof(FraudProof),
+}
+
+/// Payload for CHANNEL_SYNC messages.
+///
+/// Sent after crash recovery when local channel state may be behind the
+/// counterparty. Carries the sender's latest signed state; balances are from
+/// the sender's perspective (`initiator` is the sender).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ChannelSyncPayload {
+    /// Channel identifier
+    pub channel_id: Hash,
+    /// Highest nonce the sender holds
+    pub nonce: u64,
+    /// Balances at that nonce (sender as initiator)
+    pub balances: ChannelBalances,
+    /// Sender's signature over the close message for this state
+    pub signature: Signature,
+}
+
+/// Payload for CHANNEL_SYNC_RESPONSE messages.
+///
+/// The responder's latest signed state after applying the request. The
+/// requester adopts it if its nonce is higher than its own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ChannelSyncResponsePayload {
+    /// Channel identifier (echoed from sync request)
+    pub channel_id: Hash,
+    /// Highest nonce the responder holds
+    pub nonce: u64,
+    /// Balances at that nonce (responder as initiator)
+    pub balances: ChannelBalances,
+    /// Responder's signature over the close message for this state
+    pub signature: Signature,
+}
+
 // =============================================================================
 // Settlement Payloads (§6.7)
 // =============================================================================
@@ -469,12 +1063,16 @@ pub struct PingPayload {
 
 /// Payload for PONG messages.
 ///
-/// Response to PING with echoed nonce.
+/// Response to PING with echoed nonce, and the responder's clock so the
+/// pinger can estimate how far its own clock is off.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct PongPayload {
     /// Echoed nonce from PING
     pub nonce: u64,
+    /// Responder's unadjusted clock when replying (0 if not reported)
+    #[serde(default)]
+    pub timestamp: Timestamp,
 }
 
 /// Payload for PEER_INFO messages.
@@ -490,69 +1088,517 @@ pub struct PeerInfoPayload {
     /// Multiaddrs for this peer
     pub addresses: Vec<String>,
     /// Supported capabilities
+    ///
+    /// Capabilities this node doesn't know are dropped when decoding, so
+    /// newer peers can advertise more.
+    #[serde(deserialize_with = "deserialize_known_capabilities")]
     pub capabilities: Vec<Capability>,
     /// Number of content items hosted
     pub content_count: u64,
     /// Uptime in seconds
     pub uptime: u64,
+    /// `did:key` identifier of `public_key`, for peers that advertise one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub did: Option<String>,
+}
+
+impl PeerInfoPayload {
+    /// Whether the peer advertises `capability`.
+    pub fn supports(&self, capability: Capability) -> bool {
+        self.capabilities.contains(&capability)
+    }
 }
 
 /// Peer capabilities.
+///
+/// Each capability is one bit, so a set of them fits in a byte (see
+/// [`Capability::to_bits`]).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[repr(u8)]
 #[non_exhaustive]
 pub enum Capability {
-    /// Can serve query requests
+    /// Serves content: answers previews, queries and searches
     Query = 0x01,
     /// Supports payment channels
     Channel = 0x02,
-    /// Can initiate settlements
+    /// Settlement enabled: can initiate settlements
     Settle = 0x04,
     /// Participates in DHT indexing
     Index = 0x08,
+    /// Relays other nodes' queries
+    Relay = 0x10,
+    /// Watches channels for disputes on behalf of other peers
+    Watchtower = 0x20,
+    /// Serves compressed version deltas
+    Compression = 0x40,
+    /// Serves content chunk by chunk for multi-provider downloads
+    ChunkedTransfer = 0x80,
 }
 
 impl Capability {
+    /// Every known capability, in bit order.
+    pub const ALL: [Capability; 8] = [
+        Capability::Query,
+        Capability::Channel,
+        Capability::Settle,
+        Capability::Index,
+        Capability::Relay,
+        Capability::Watchtower,
+        Capability::Compression,
+        Capability::ChunkedTransfer,
+    ];
+
     /// Convert a u8 value to a Capability.
     pub fn from_u8(value: u8) -> Option<Self> {
-        match value {
-            0x01 => Some(Capability::Query),
-            0x02 => Some(Capability::Channel),
-            0x04 => Some(Capability::Settle),
-            0x08 => Some(Capability::Index),
-            _ => None,
-        }
+        Self::ALL.into_iter().find(|c| c.to_u8() == value)
     }
 
     /// Convert to u8 value.
     pub fn to_u8(self) -> u8 {
         self as u8
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use nodalync_crypto::content_hash;
+    /// Short name, e.g. `chunked-transfer`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Capability::Query => "serves-content",
+            Capability::Channel => "channels",
+            Capability::Settle => "settlement-enabled",
+            Capability::Index => "index",
+            Capability::Relay => "relays",
+            Capability::Watchtower => "watchtower",
+            Capability::Compression => "compression",
+            Capability::ChunkedTransfer => "chunked-transfer",
+        }
+    }
 
-    fn test_hash(data: &[u8]) -> Hash {
-        content_hash(data)
+    /// Parse a capability from its [`name`](Self::name).
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.name() == name)
     }
 
-    fn test_l1_summary() -> L1Summary {
-        L1Summary::empty(test_hash(b"source"))
+    /// Pack capabilities into a bit set.
+    pub fn to_bits(capabilities: &[Capability]) -> u8 {
+        capabilities.iter().fold(0, |bits, c| bits | c.to_u8())
     }
 
-    #[test]
-    fn test_announce_payload_serialization() {
-        let payload = AnnouncePayload {
-            hash: test_hash(b"content"),
-            content_type: ContentType::L0,
-            title: "Test Content".to_string(),
-            l1_summary: test_l1_summary(),
+    /// Unpack a bit set, ignoring unknown bits.
+    pub fn from_bits(bits: u8) -> Vec<Capability> {
+        Self::ALL
+            .into_iter()
+            .filter(|c| bits & c.to_u8() != 0)
+            .collect()
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Decode a capability list, dropping capabilities we don't know.
+fn deserialize_known_capabilities<'de, D>(deserializer: D) -> Result<Vec<Capability>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum MaybeCapability {
+        Known(Capability),
+        Unknown(serde::de::IgnoredAny),
+    }
+
+    let capabilities = Vec::<MaybeCapability>::deserialize(deserializer)?;
+    Ok(capabilities
+        .into_iter()
+        .filter_map(|c| match c {
+            MaybeCapability::Known(c) => Some(c),
+            MaybeCapability::Unknown(_) => None,
+        })
+        .collect())
+}
+
+// =============================================================================
+// Invoice Payloads (§6.9)
+// =============================================================================
+
+/// Payload for INVOICE_REQUEST messages.
+///
+/// Asks the payee for an invoice whose hash was shared out of band. The
+/// payee answers with INVOICE, or INVOICE_ACK if it has no such invoice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct InvoiceRequestPayload {
+    /// Hash of the requested invoice
+    pub invoice_hash: Hash,
+}
+
+/// Payload for INVOICE messages.
+///
+/// Carries a signed invoice from its payee, either pushed to the payer or
+/// in response to INVOICE_REQUEST.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct InvoicePayload {
+    /// The signed invoice
+    pub invoice: Invoice,
+}
+
+/// Payload for INVOICE_PAY messages.
+///
+/// Pays an invoice with a signed payment over the channel between payer
+/// and payee. The payment's `query_hash` is the invoice hash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct InvoicePayPayload {
+    /// Hash of the invoice being paid
+    pub invoice_hash: Hash,
+    /// Signed channel payment
+    pub payment: Payment,
+    /// Payment nonce for replay protection (must be > channel nonce)
+    pub payment_nonce: u64,
+}
+
+/// Payload for INVOICE_ACK messages.
+///
+/// Acknowledges a delivered or paid invoice, or reports why it was refused.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct InvoiceAckPayload {
+    /// Hash of the invoice
+    pub invoice_hash: Hash,
+    /// Whether the invoice (or payment) was accepted
+    pub accepted: bool,
+    /// Why it was refused
+    pub reason: Option<String>,
+}
+
+// =============================================================================
+// Group Payloads
+// =============================================================================
+
+/// Payload for GROUP_REQUEST messages.
+///
+/// Asks a group owner for its current membership list, to resolve or
+/// refresh access for content that names the group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct GroupRequestPayload {
+    /// ID of the requested group
+    pub group_id: Hash,
+}
+
+/// Payload for GROUP_RESPONSE messages.
+///
+/// Carries the owner's signed membership list, or `None` if it owns no
+/// group with the requested ID.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct GroupResponsePayload {
+    /// The signed membership list
+    pub group: Option<Group>,
+}
+
+// =============================================================================
+// Snapshot Payloads
+// =============================================================================
+
+/// Payload for SNAPSHOT_REQUEST messages.
+///
+/// Asks a well-connected peer for a signed snapshot of what it knows, so a
+/// new node can start from it instead of waiting on gossip. The peer may
+/// return less than asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct SnapshotRequestPayload {
+    /// Most announcements wanted
+    pub max_announcements: u32,
+    /// Most peers wanted
+    pub max_peers: u32,
+}
+
+/// Payload for SNAPSHOT_RESPONSE messages.
+///
+/// Carries the signed snapshot, or `None` if the peer doesn't serve them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct SnapshotResponsePayload {
+    /// The signed snapshot
+    pub snapshot: Option<StateSnapshot>,
+}
+
+/// A signed snapshot of a node's announcement index and peer list.
+///
+/// The issuer signs all other fields. Each announcement keeps its own
+/// publisher signature, so the issuer vouches for the selection but can't
+/// alter what publishers announced.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct StateSnapshot {
+    /// The node that took the snapshot
+    pub issuer: PeerId,
+    /// The issuer's identity key, which `signature` verifies against
+    pub issuer_key: PublicKey,
+    /// When the snapshot was taken
+    pub created_at: Timestamp,
+    /// Announcements held by the issuer, most recently received first
+    pub announcements: Vec<AnnouncePayload>,
+    /// Peers known to the issuer, most recently seen first
+    pub peers: Vec<SnapshotPeer>,
+    /// The issuer's signature over all other fields
+    pub signature: Signature,
+}
+
+/// A peer record in a [`StateSnapshot`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct SnapshotPeer {
+    /// Peer identifier
+    pub peer_id: PeerId,
+    /// Peer's public key
+    pub public_key: PublicKey,
+    /// Multiaddrs the issuer knows for the peer
+    pub addresses: Vec<String>,
+    /// The peer's libp2p peer ID (base58 encoded), if the issuer knows it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub libp2p_peer_id: Option<String>,
+    /// When the issuer last saw the peer
+    pub last_seen: Timestamp,
+}
+
+// =============================================================================
+// Sync Payloads
+// =============================================================================
+
+/// A device's state, encrypted under the owner's password.
+///
+/// Only `owner` and `created_at` are in the clear, so a peer holding the
+/// bundle for the owner learns nothing else about it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct EncryptedSyncBundle {
+    /// The identity the bundle belongs to
+    pub owner: PeerId,
+    /// When the bundle was exported
+    pub created_at: Timestamp,
+    /// The encrypted bundle
+    pub ciphertext: Vec<u8>,
+}
+
+/// Payload for SYNC_PUSH messages.
+///
+/// Leaves a bundle with a peer for the owner's other devices to pull. The
+/// message must be signed by the bundle's owner.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct SyncPushPayload {
+    /// The bundle to hold
+    pub bundle: EncryptedSyncBundle,
+}
+
+/// Payload for SYNC_PUSH_ACK messages.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct SyncPushAckPayload {
+    /// Whether the peer now holds the bundle
+    pub stored: bool,
+    /// Why it wasn't stored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Payload for SYNC_PULL messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct SyncPullPayload {
+    /// The identity whose bundle is wanted
+    pub owner: PeerId,
+}
+
+/// Payload for SYNC_PULL_RESPONSE messages.
+///
+/// Carries the latest bundle held for the owner, if any.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct SyncPullResponsePayload {
+    /// The bundle held
+    pub bundle: Option<EncryptedSyncBundle>,
+}
+
+// =============================================================================
+// Availability Checks
+// =============================================================================
+
+/// Most addresses the checker dials for one availability check.
+pub const MAX_AVAILABILITY_ADDRESSES: usize = 4;
+
+/// Payload for AVAILABILITY_CHECK messages.
+///
+/// Asks the receiver to dial the sender at one of `addresses` over a new
+/// connection and preview `hash`, so a publisher learns whether its
+/// content is fetchable from outside. Only the sender's own content is
+/// checked.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct AvailabilityCheckPayload {
+    /// Content to preview
+    pub hash: Hash,
+    /// The sender's public addresses, tried in order (at most
+    /// `MAX_AVAILABILITY_ADDRESSES`)
+    pub addresses: Vec<String>,
+}
+
+/// Payload for AVAILABILITY_RESULT messages.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct AvailabilityResultPayload {
+    /// Content that was checked
+    pub hash: Hash,
+    /// Address the checker reached the sender at, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dialed: Option<String>,
+    /// Whether the preview came back with the content's manifest
+    pub previewed: bool,
+    /// Round trip of the preview in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u32>,
+    /// Why the check failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AvailabilityResultPayload {
+    /// Whether the content was reachable.
+    pub fn reachable(&self) -> bool {
+        self.dialed.is_some() && self.previewed
+    }
+}
+
+// =============================================================================
+// Replica Catalogs
+// =============================================================================
+
+/// A primary's catalog, exported for a replica to serve.
+///
+/// Moved as a file rather than a message. Signed by the primary over every
+/// other field, so the manifests can't be altered on the way; content is
+/// checked against its manifest hash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ReplicaCatalog {
+    /// The primary's delegation naming the replica
+    pub delegation: ReplicaDelegation,
+    /// When the catalog was exported
+    pub created_at: Timestamp,
+    /// Published content with its manifests
+    pub items: Vec<ReplicaItem>,
+    /// Primary's signature over the catalog with a zeroed signature
+    pub signature: Signature,
+}
+
+/// A manifest and its content in a [`ReplicaCatalog`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ReplicaItem {
+    /// The primary's manifest
+    pub manifest: Manifest,
+    /// The content it describes
+    pub content: Vec<u8>,
+}
+
+// =============================================================================
+// Storefronts
+// =============================================================================
+
+/// A publisher's catalog, for consumers to browse in one fetch.
+///
+/// Stored in the DHT under [`storefront_key`](crate::storefront_key) of
+/// the publisher, and regenerated as the catalog changes. Signed by the
+/// publisher over every other field; each item's terms are still checked
+/// against its manifest when queried.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Storefront {
+    /// The publisher whose catalog this is
+    pub publisher: PeerId,
+    /// The publisher's identity key, which `signature` verifies against
+    pub publisher_key: PublicKey,
+    /// Display name of the storefront
+    pub name: String,
+    /// When the storefront was generated
+    pub created_at: Timestamp,
+    /// Categories items are filed under, in display order
+    pub categories: Vec<String>,
+    /// Listed content, featured items first
+    pub items: Vec<StorefrontItem>,
+    /// Publisher's signature over the storefront with a zeroed signature
+    pub signature: Signature,
+}
+
+impl Storefront {
+    /// Items marked as featured.
+    pub fn featured(&self) -> impl Iterator<Item = &StorefrontItem> {
+        self.items.iter().filter(|item| item.featured)
+    }
+
+    /// Items filed under a category.
+    pub fn in_category<'a>(
+        &'a self,
+        category: &'a str,
+    ) -> impl Iterator<Item = &'a StorefrontItem> + 'a {
+        self.items
+            .iter()
+            .filter(move |item| item.category.as_deref() == Some(category))
+    }
+}
+
+/// A content listing in a [`Storefront`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct StorefrontItem {
+    /// Content hash
+    pub hash: Hash,
+    /// Content title
+    pub title: String,
+    /// Content type
+    pub content_type: ContentType,
+    /// Price per query
+    pub price: Amount,
+    /// Category the item is filed under, one of the storefront's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Whether the publisher features the item
+    #[serde(default)]
+    pub featured: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodalync_crypto::content_hash;
+
+    fn test_hash(data: &[u8]) -> Hash {
+        content_hash(data)
+    }
+
+    fn test_l1_summary() -> L1Summary {
+        L1Summary::empty(test_hash(b"source"))
+    }
+
+    #[test]
+    fn test_announce_payload_serialization() {
+        let payload = AnnouncePayload {
+            hash: test_hash(b"content"),
+            content_type: ContentType::L0,
+            title: "Test Content".to_string(),
+            l1_summary: test_l1_summary(),
             price: 100,
             addresses: vec!["/ip4/127.0.0.1/tcp/9000".to_string()],
             publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
         };
 
         let json = serde_json::to_string(&payload).unwrap();
@@ -574,6 +1620,9 @@ mod tests {
             publisher_peer_id: Some(
                 "12D3KooWLvP5fP18r2B1xLV21eq9JyMzkySxvdTdWvuaxzVcs289".to_string(),
             ),
+            publisher_key: None,
+            signature: None,
+            delegation: None,
         };
 
         // Test CBOR encoding/decoding (what the wire uses)
@@ -590,6 +1639,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_announce_payload_signature_cbor() {
+        let payload = AnnouncePayload {
+            hash: test_hash(b"content"),
+            content_type: ContentType::L0,
+            title: "Test Content".to_string(),
+            l1_summary: test_l1_summary(),
+            price: 100,
+            addresses: vec![],
+            publisher_peer_id: None,
+            publisher_key: Some(PublicKey([3u8; 32])),
+            signature: Some(Signature([4u8; 64])),
+            delegation: None,
+        };
+
+        let mut cbor_buf = Vec::new();
+        ciborium::into_writer(&payload, &mut cbor_buf).unwrap();
+        let decoded: AnnouncePayload = ciborium::from_reader(&cbor_buf[..]).unwrap();
+        assert_eq!(decoded, payload);
+    }
+
     #[test]
     fn test_announce_payload_backwards_compatible() {
         // Test that decoding an old CBOR payload (without publisher_peer_id) works
@@ -602,6 +1672,9 @@ mod tests {
             price: 0,
             addresses: vec![],
             publisher_peer_id: None,
+            publisher_key: None,
+            signature: None,
+            delegation: None,
         };
 
         // Encode without publisher_peer_id
@@ -619,6 +1692,53 @@ mod tests {
         assert!(filters.content_types.is_none());
         assert!(filters.max_price.is_none());
         assert!(filters.tags.is_none());
+        assert!(filters.is_empty());
+    }
+
+    #[test]
+    fn test_search_filters_matches_result() {
+        let result = SearchResult {
+            hash: test_hash(b"result"),
+            content_type: ContentType::L3,
+            title: "Result".to_string(),
+            owner: PeerId([1u8; 20]),
+            l1_summary: test_l1_summary(),
+            price: 100,
+            total_queries: 0,
+            relevance_score: 1.0,
+            publisher_addresses: vec![],
+        };
+        assert!(SearchFilters::default().matches_result(&result));
+
+        let filters = SearchFilters {
+            content_types: Some(vec![ContentType::L0, ContentType::L3]),
+            min_price: Some(100),
+            max_price: Some(100),
+            owner: Some(PeerId([1u8; 20])),
+            // Not checked against results
+            tags: Some(vec!["physics".to_string()]),
+            ..Default::default()
+        };
+        assert!(!filters.is_empty());
+        assert!(filters.matches_result(&result));
+
+        let too_cheap = SearchFilters {
+            min_price: Some(101),
+            ..Default::default()
+        };
+        assert!(!too_cheap.matches_result(&result));
+
+        let other_owner = SearchFilters {
+            owner: Some(PeerId([2u8; 20])),
+            ..Default::default()
+        };
+        assert!(!other_owner.matches_result(&result));
+
+        let other_type = SearchFilters {
+            content_types: Some(vec![ContentType::L0]),
+            ..Default::default()
+        };
+        assert!(!other_type.matches_result(&result));
     }
 
     #[test]
@@ -639,6 +1759,10 @@ mod tests {
         assert_eq!(Capability::Channel as u8, 0x02);
         assert_eq!(Capability::Settle as u8, 0x04);
         assert_eq!(Capability::Index as u8, 0x08);
+        assert_eq!(Capability::Relay as u8, 0x10);
+        assert_eq!(Capability::Watchtower as u8, 0x20);
+        assert_eq!(Capability::Compression as u8, 0x40);
+        assert_eq!(Capability::ChunkedTransfer as u8, 0x80);
     }
 
     #[test]
@@ -647,9 +1771,24 @@ mod tests {
         assert_eq!(Capability::from_u8(0x02), Some(Capability::Channel));
         assert_eq!(Capability::from_u8(0x04), Some(Capability::Settle));
         assert_eq!(Capability::from_u8(0x08), Some(Capability::Index));
+        assert_eq!(Capability::from_u8(0x80), Some(Capability::ChunkedTransfer));
         assert_eq!(Capability::from_u8(0xFF), None);
     }
 
+    #[test]
+    fn test_capability_bits_and_names() {
+        let capabilities = vec![Capability::Query, Capability::Relay];
+        assert_eq!(Capability::to_bits(&capabilities), 0x11);
+        assert_eq!(Capability::from_bits(0x11), capabilities);
+        assert_eq!(Capability::from_bits(0xFF), Capability::ALL.to_vec());
+
+        for capability in Capability::ALL {
+            assert_eq!(Capability::from_name(capability.name()), Some(capability));
+        }
+        assert_eq!(Capability::ChunkedTransfer.to_string(), "chunked-transfer");
+        assert_eq!(Capability::from_name("teleport"), None);
+    }
+
     #[test]
     fn test_ping_pong_roundtrip() {
         let ping = PingPayload { nonce: 12345 };
@@ -657,8 +1796,17 @@ mod tests {
         let deserialized: PingPayload = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized.nonce, ping.nonce);
 
-        let pong = PongPayload { nonce: ping.nonce };
+        let pong = PongPayload {
+            nonce: ping.nonce,
+            timestamp: 1_700_000_000_000,
+        };
         assert_eq!(pong.nonce, ping.nonce);
+        let json = serde_json::to_string(&pong).unwrap();
+        assert_eq!(serde_json::from_str::<PongPayload>(&json).unwrap(), pong);
+
+        // Pongs without a timestamp still decode
+        let legacy: PongPayload = serde_json::from_str(r#"{"nonce":12345}"#).unwrap();
+        assert_eq!(legacy.timestamp, 0);
     }
 
     #[test]
@@ -669,6 +1817,9 @@ mod tests {
             message: Some("Content not found".to_string()),
             required_channel_peer_id: None,
             required_channel_libp2p_peer: None,
+            challenge: None,
+            retry_after_ms: None,
+            alternative_providers: vec![],
         };
 
         let json = serde_json::to_string(&payload).unwrap();
@@ -689,6 +1840,9 @@ mod tests {
             required_channel_libp2p_peer: Some(
                 "12D3KooWLvP5fP18r2B1xLV21eq9JyMzkySxvdTdWvuaxzVcs289".to_string(),
             ),
+            challenge: None,
+            retry_after_ms: None,
+            alternative_providers: vec![],
         };
 
         let mut cbor_buf = Vec::new();
@@ -700,6 +1854,91 @@ mod tests {
         assert!(decoded.required_channel_libp2p_peer.is_some());
     }
 
+    #[test]
+    fn test_query_error_retry_and_alternatives() {
+        let mut payload =
+            QueryErrorPayload::new(test_hash(b"content"), ErrorCode::RateLimited, "server busy");
+        payload.retry_after_ms = Some(1500);
+        payload.alternative_providers =
+            vec!["12D3KooWLvP5fP18r2B1xLV21eq9JyMzkySxvdTdWvuaxzVcs289".to_string()];
+
+        let mut buf = Vec::new();
+        ciborium::into_writer(&payload, &mut buf).unwrap();
+        let decoded: QueryErrorPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, payload);
+
+        // Errors from nodes that don't send the new fields still decode
+        let plain = QueryErrorPayload::new(test_hash(b"content"), ErrorCode::NotFound, "gone");
+        let json = serde_json::to_string(&plain).unwrap();
+        assert!(!json.contains("retry_after_ms"));
+        assert!(!json.contains("alternative_providers"));
+        let decoded: QueryErrorPayload = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.retry_after_ms, None);
+        assert!(decoded.alternative_providers.is_empty());
+    }
+
+    #[test]
+    fn test_query_error_code_and_retryable() {
+        let mut payload =
+            QueryErrorPayload::new(test_hash(b"content"), ErrorCode::NotFound, "gone");
+        assert_eq!(payload.code(), 0x0001);
+        assert!(!payload.is_retryable());
+
+        // A retry delay makes any error worth retrying
+        payload.retry_after_ms = Some(1000);
+        assert!(payload.is_retryable());
+
+        let busy = QueryErrorPayload::new(test_hash(b"content"), ErrorCode::RateLimited, "busy");
+        assert!(busy.is_retryable());
+    }
+
+    #[test]
+    fn test_query_challenge_cbor_roundtrip() {
+        let challenge = QueryChallenge {
+            nonce: test_hash(b"nonce"),
+            provider: PeerId([2u8; 20]),
+            expires_at: 1234567890,
+        };
+        let payload = QueryErrorPayload {
+            hash: test_hash(b"content"),
+            error_code: ErrorCode::ChallengeRequired,
+            message: None,
+            required_channel_peer_id: None,
+            required_channel_libp2p_peer: None,
+            challenge: Some(challenge.clone()),
+            retry_after_ms: None,
+            alternative_providers: vec![],
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&payload, &mut buf).unwrap();
+        let decoded: QueryErrorPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, payload);
+
+        let request = QueryRequestPayload {
+            hash: test_hash(b"content"),
+            query: None,
+            payment: None,
+            version_spec: None,
+            payment_nonce: 0,
+            capability: None,
+            recipient_key: None,
+            challenge_response: Some(ChallengeResponse {
+                nonce: challenge.nonce,
+                public_key: PublicKey::from_bytes([5u8; 32]),
+                signature: Signature::from_bytes([6u8; 64]),
+            }),
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&request, &mut buf).unwrap();
+        let decoded: QueryRequestPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, request);
+    }
+
     fn test_manifest(
         hash: Hash,
         content_type: ContentType,
@@ -766,6 +2005,8 @@ mod tests {
                 created_after: Some(1000000),
                 created_before: Some(2000000),
                 tags: Some(vec!["physics".to_string(), "quantum".to_string()]),
+                min_price: Some(100),
+                owner: Some(PeerId([7u8; 20])),
             }),
             limit: 20,
             offset: 5,
@@ -822,6 +2063,16 @@ mod tests {
                 Visibility::Shared,
             ),
             l1_summary: test_l1_summary(),
+            collection: None,
+            forks: vec![test_hash(b"fork")],
+            canonical: Some(CanonicalVersion::new(
+                test_hash(b"root"),
+                hash,
+                PeerId([2u8; 20]),
+                PublicKey([3u8; 32]),
+                1234567890,
+            )),
+            window_queries: Some(7),
         };
         let mut buf = Vec::new();
         ciborium::into_writer(&payload, &mut buf).unwrap();
@@ -835,7 +2086,7 @@ mod tests {
         let payload = QueryRequestPayload {
             hash,
             query: Some("tell me about X".to_string()),
-            payment: Payment::new(
+            payment: Some(Payment::new(
                 test_hash(b"pay-id"),
                 test_hash(b"channel"),
                 100,
@@ -844,14 +2095,71 @@ mod tests {
                 vec![],
                 1234567890,
                 Signature::from_bytes([0u8; 64]),
-            ),
+            )),
             version_spec: Some(VersionSpec::Latest),
             payment_nonce: 5,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: Some(RequestId(0x00ab_cdef_0123_4567)),
+            section: Some(2),
+            unit: None,
+            referrer: None,
+            entitlement: None,
         };
         let mut buf = Vec::new();
         ciborium::into_writer(&payload, &mut buf).unwrap();
         let decoded: QueryRequestPayload = ciborium::from_reader(&buf[..]).unwrap();
         assert_eq!(decoded, payload);
+
+        // Free queries carry no payment at all
+        let free = QueryRequestPayload {
+            hash,
+            query: None,
+            payment: None,
+            version_spec: None,
+            payment_nonce: 0,
+            capability: None,
+            recipient_key: None,
+            challenge_response: None,
+            request_id: None,
+            section: None,
+            unit: None,
+            referrer: None,
+            entitlement: None,
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&free, &mut buf).unwrap();
+        let decoded: QueryRequestPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, free);
+
+        // Re-downloads carry the earlier receipt instead of a payment
+        let redownload = QueryRequestPayload {
+            entitlement: Some(PaymentReceipt {
+                payment_id: test_hash(b"receipt"),
+                amount: 50,
+                timestamp: 1234567890,
+                channel_nonce: 3,
+                distributor_signature: Signature::from_bytes([1u8; 64]),
+                app_fee: 0,
+                app_fee_recipient: None,
+                referral_fee: 0,
+                referrer: None,
+            }),
+            ..free
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&redownload, &mut buf).unwrap();
+        let decoded: QueryRequestPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, redownload);
+    }
+
+    #[test]
+    fn test_request_id_display_roundtrip() {
+        let id = RequestId(0x00ab_cdef_0123_4567);
+        assert_eq!(id.to_string(), "00abcdef01234567");
+        assert_eq!("00abcdef01234567".parse::<RequestId>().unwrap(), id);
+        assert!("not hex".parse::<RequestId>().is_err());
     }
 
     #[test]
@@ -859,7 +2167,7 @@ mod tests {
         let hash = test_hash(b"responded");
         let payload = QueryResponsePayload {
             hash,
-            content: b"the full content bytes here".to_vec(),
+            content: b"the full content bytes here".to_vec().into(),
             manifest: test_manifest(
                 hash,
                 ContentType::L0,
@@ -873,7 +2181,21 @@ mod tests {
                 timestamp: 1234567890,
                 channel_nonce: 3,
                 distributor_signature: Signature::from_bytes([1u8; 64]),
+                app_fee: 0,
+                app_fee_recipient: None,
+                referral_fee: 0,
+                referrer: None,
             },
+            bundle: vec![],
+            content_key: None,
+            delivery: Some(DeliveryCommitment {
+                served_hash: hash,
+                provider_key: PublicKey([5u8; 32]),
+                signature: Signature::from_bytes([6u8; 64]),
+            }),
+            section: None,
+            unit: Some(4),
+            trial_reads_left: None,
         };
         let mut buf = Vec::new();
         ciborium::into_writer(&payload, &mut buf).unwrap();
@@ -881,6 +2203,95 @@ mod tests {
         assert_eq!(decoded, payload);
     }
 
+    #[test]
+    fn test_content_bytes_encoding() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let shared = ContentBytes::shared(bytes.clone());
+        assert!(shared.is_shared());
+        assert_eq!(shared, bytes);
+        assert_eq!(shared, ContentBytes::from(bytes.clone()));
+
+        // Shared bytes encode exactly like a Vec, and decode as owned
+        let mut expected = Vec::new();
+        ciborium::into_writer(&bytes, &mut expected).unwrap();
+        let mut buf = Vec::new();
+        ciborium::into_writer(&shared, &mut buf).unwrap();
+        assert_eq!(buf, expected);
+        let decoded: ContentBytes = ciborium::from_reader(&buf[..]).unwrap();
+        assert!(!decoded.is_shared());
+        assert_eq!(decoded.into_vec(), bytes);
+    }
+
+    #[test]
+    fn test_collection_payloads_cbor_roundtrip() {
+        let hash = test_hash(b"collection");
+        let item_hash = test_hash(b"item");
+        let manifest = test_manifest(
+            hash,
+            ContentType::Collection,
+            PeerId([4u8; 20]),
+            50,
+            Visibility::Shared,
+        );
+        let item_manifest = test_manifest(
+            item_hash,
+            ContentType::L0,
+            PeerId([4u8; 20]),
+            10,
+            Visibility::Shared,
+        );
+
+        let preview = PreviewResponsePayload {
+            hash,
+            manifest: manifest.clone(),
+            l1_summary: test_l1_summary(),
+            collection: Some(
+                Collection::new("Reading list")
+                    .with_item(nodalync_types::CollectionItem::new(item_hash, "Item"))
+                    .with_bundle_price(50),
+            ),
+            forks: vec![],
+            canonical: None,
+            window_queries: None,
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&preview, &mut buf).unwrap();
+        let decoded: PreviewResponsePayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, preview);
+
+        let response = QueryResponsePayload {
+            hash,
+            content: b"collection json".to_vec().into(),
+            manifest,
+            payment_receipt: PaymentReceipt {
+                payment_id: test_hash(b"receipt"),
+                amount: 50,
+                timestamp: 1234567890,
+                channel_nonce: 3,
+                distributor_signature: Signature::from_bytes([1u8; 64]),
+                app_fee: 0,
+                app_fee_recipient: None,
+                referral_fee: 0,
+                referrer: None,
+            },
+            bundle: vec![BundleItem {
+                hash: item_hash,
+                manifest: item_manifest,
+                content: b"item content".to_vec(),
+                content_key: None,
+            }],
+            content_key: None,
+            delivery: None,
+            section: None,
+            unit: None,
+            trial_reads_left: None,
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&response, &mut buf).unwrap();
+        let decoded: QueryResponsePayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, response);
+    }
+
     #[test]
     fn test_payment_receipt_cbor_roundtrip() {
         let payload = PaymentReceipt {
@@ -889,6 +2300,10 @@ mod tests {
             timestamp: 9999999999,
             channel_nonce: 42,
             distributor_signature: Signature::from_bytes([7u8; 64]),
+            app_fee: 49,
+            app_fee_recipient: Some(PeerId([3u8; 20])),
+            referral_fee: 0,
+            referrer: None,
         };
         let mut buf = Vec::new();
         ciborium::into_writer(&payload, &mut buf).unwrap();
@@ -900,6 +2315,8 @@ mod tests {
     fn test_version_request_payload_cbor_roundtrip() {
         let payload = VersionRequestPayload {
             version_root: test_hash(b"version-root"),
+            delta_from: Some(test_hash(b"v1")),
+            include_manifests: true,
         };
         let mut buf = Vec::new();
         ciborium::into_writer(&payload, &mut buf).unwrap();
@@ -930,6 +2347,43 @@ mod tests {
                 },
             ],
             latest,
+            delta: None,
+            manifests: vec![test_manifest(
+                latest,
+                ContentType::L0,
+                PeerId([4u8; 20]),
+                150,
+                Visibility::Shared,
+            )],
+            canonical: None,
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&payload, &mut buf).unwrap();
+        let decoded: VersionResponsePayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, payload);
+    }
+
+    #[test]
+    fn test_version_delta_cbor_roundtrip() {
+        let base = test_hash(b"v1");
+        let latest = test_hash(b"v2");
+        let payload = VersionResponsePayload {
+            version_root: base,
+            versions: vec![],
+            latest,
+            delta: Some(VersionDelta {
+                base,
+                manifest: test_manifest(
+                    latest,
+                    ContentType::L0,
+                    PeerId([4u8; 20]),
+                    0,
+                    Visibility::Shared,
+                ),
+                delta: vec![0x28, 0xb5, 0x2f, 0xfd, 1, 2, 3],
+            }),
+            manifests: vec![],
+            canonical: None,
         };
         let mut buf = Vec::new();
         ciborium::into_writer(&payload, &mut buf).unwrap();
@@ -1030,6 +2484,435 @@ mod tests {
         assert_eq!(decoded, payload);
     }
 
+    #[test]
+    fn test_invoice_payloads_cbor_roundtrip() {
+        let (_, public_key) = nodalync_crypto::generate_identity();
+        let invoice = Invoice::new(
+            nodalync_crypto::peer_id_from_public_key(&public_key),
+            public_key,
+            250,
+            "Survey data",
+            Some(test_hash(b"dataset")),
+            1_000,
+            2_000,
+        );
+
+        let payload = InvoicePayload {
+            invoice: invoice.clone(),
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&payload, &mut buf).unwrap();
+        let decoded: InvoicePayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, payload);
+
+        let ack = InvoiceAckPayload {
+            invoice_hash: invoice.hash,
+            accepted: false,
+            reason: Some("expired".to_string()),
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&ack, &mut buf).unwrap();
+        let decoded: InvoiceAckPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, ack);
+    }
+
+    #[test]
+    fn test_group_payloads_cbor_roundtrip() {
+        let (_, public_key) = nodalync_crypto::generate_identity();
+        let group = Group::new(
+            nodalync_crypto::peer_id_from_public_key(&public_key),
+            public_key,
+            "research",
+            vec![PeerId([1u8; 20])],
+            1,
+            1_000,
+        );
+
+        let request = GroupRequestPayload { group_id: group.id };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&request, &mut buf).unwrap();
+        let decoded: GroupRequestPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, request);
+
+        for payload in [
+            GroupResponsePayload { group: Some(group) },
+            GroupResponsePayload { group: None },
+        ] {
+            let mut buf = Vec::new();
+            ciborium::into_writer(&payload, &mut buf).unwrap();
+            let decoded: GroupResponsePayload = ciborium::from_reader(&buf[..]).unwrap();
+            assert_eq!(decoded, payload);
+        }
+    }
+
+    #[test]
+    fn test_tombstone_payload_cbor_roundtrip() {
+        let (_, public_key) = nodalync_crypto::generate_identity();
+        let payload = TombstonePayload {
+            tombstone: Tombstone::new(
+                test_hash(b"withdrawn"),
+                nodalync_crypto::peer_id_from_public_key(&public_key),
+                public_key,
+                1_000,
+            ),
+        };
+
+        let mut buf = Vec::new();
+        ciborium::into_writer(&payload, &mut buf).unwrap();
+        let decoded: TombstonePayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, payload);
+    }
+
+    #[test]
+    fn test_report_payload_cbor_roundtrip() {
+        let (_, public_key) = nodalync_crypto::generate_identity();
+        let payload = ReportPayload {
+            report: ContentReport::new(
+                test_hash(b"reported"),
+                nodalync_crypto::peer_id_from_public_key(&public_key),
+                public_key,
+                nodalync_types::ReportReason::Copyright,
+                "Copied from my paper",
+                1_000,
+            ),
+        };
+
+        let mut buf = Vec::new();
+        ciborium::into_writer(&payload, &mut buf).unwrap();
+        let decoded: ReportPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, payload);
+    }
+
+    #[test]
+    fn test_fraud_proof_payload_cbor_roundtrip() {
+        let (_, public_key) = nodalync_crypto::generate_identity();
+        let payload = FraudProofPayload {
+            proof: FraudProof::new(
+                test_hash(b"requested"),
+                test_hash(b"payment"),
+                PeerId([4u8; 20]),
+                DeliveryCommitment {
+                    served_hash: test_hash(b"served"),
+                    provider_key: PublicKey([5u8; 32]),
+                    signature: Signature::from_bytes([6u8; 64]),
+                },
+                nodalync_crypto::peer_id_from_public_key(&public_key),
+                public_key,
+                1_000,
+            ),
+        };
+
+        let mut buf = Vec::new();
+        ciborium::into_writer(&payload, &mut buf).unwrap();
+        let decoded: FraudProofPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, payload);
+    }
+
+    #[test]
+    fn test_revocation_payload_cbor_roundtrip() {
+        let (_, public_key) = nodalync_crypto::generate_identity();
+        let payload = RevocationPayload {
+            certificate: RevocationCertificate::new(
+                nodalync_crypto::peer_id_from_public_key(&public_key),
+                public_key,
+                1_000,
+            ),
+        };
+
+        let mut buf = Vec::new();
+        ciborium::into_writer(&payload, &mut buf).unwrap();
+        let decoded: RevocationPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, payload);
+    }
+
+    #[test]
+    fn test_storefront_cbor_roundtrip() {
+        let (_, public_key) = nodalync_crypto::generate_identity();
+        let storefront = Storefront {
+            publisher: nodalync_crypto::peer_id_from_public_key(&public_key),
+            publisher_key: public_key,
+            name: "Field Guides".to_string(),
+            created_at: 1_000,
+            categories: vec!["birds".to_string()],
+            items: vec![
+                StorefrontItem {
+                    hash: test_hash(b"owls"),
+                    title: "Owls".to_string(),
+                    content_type: ContentType::L0,
+                    price: 100,
+                    category: Some("birds".to_string()),
+                    featured: true,
+                },
+                StorefrontItem {
+                    hash: test_hash(b"moss"),
+                    title: "Moss".to_string(),
+                    content_type: ContentType::L0,
+                    price: 0,
+                    category: None,
+                    featured: false,
+                },
+            ],
+            signature: Signature::from_bytes([2u8; 64]),
+        };
+
+        let mut buf = Vec::new();
+        ciborium::into_writer(&storefront, &mut buf).unwrap();
+        let decoded: Storefront = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, storefront);
+        assert_eq!(decoded.featured().count(), 1);
+        assert_eq!(decoded.in_category("birds").next().unwrap().title, "Owls");
+    }
+
+    #[test]
+    fn test_snapshot_payloads_cbor_roundtrip() {
+        let (_, public_key) = nodalync_crypto::generate_identity();
+        let hash = test_hash(b"announced");
+        let snapshot = StateSnapshot {
+            issuer: nodalync_crypto::peer_id_from_public_key(&public_key),
+            issuer_key: public_key,
+            created_at: 1_000,
+            announcements: vec![AnnouncePayload {
+                hash,
+                content_type: ContentType::L0,
+                title: "Announced".to_string(),
+                l1_summary: L1Summary::empty(hash),
+                price: 100,
+                addresses: vec!["/ip4/127.0.0.1/tcp/9000".to_string()],
+                publisher_peer_id: None,
+                publisher_key: None,
+                signature: None,
+                delegation: None,
+            }],
+            peers: vec![SnapshotPeer {
+                peer_id: PeerId([1u8; 20]),
+                public_key,
+                addresses: vec![],
+                libp2p_peer_id: Some("12D3KooWPeer".to_string()),
+                last_seen: 900,
+            }],
+            signature: Signature::from_bytes([2u8; 64]),
+        };
+
+        let request = SnapshotRequestPayload {
+            max_announcements: 100,
+            max_peers: 10,
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&request, &mut buf).unwrap();
+        let decoded: SnapshotRequestPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, request);
+
+        for payload in [
+            SnapshotResponsePayload {
+                snapshot: Some(snapshot),
+            },
+            SnapshotResponsePayload { snapshot: None },
+        ] {
+            let mut buf = Vec::new();
+            ciborium::into_writer(&payload, &mut buf).unwrap();
+            let decoded: SnapshotResponsePayload = ciborium::from_reader(&buf[..]).unwrap();
+            assert_eq!(decoded, payload);
+        }
+    }
+
+    #[test]
+    fn test_sync_payloads_cbor_roundtrip() {
+        let bundle = EncryptedSyncBundle {
+            owner: PeerId([3u8; 20]),
+            created_at: 1_000,
+            ciphertext: vec![1, 2, 3, 4],
+        };
+
+        let push = SyncPushPayload {
+            bundle: bundle.clone(),
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&push, &mut buf).unwrap();
+        let decoded: SyncPushPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, push);
+
+        let ack = SyncPushAckPayload {
+            stored: false,
+            reason: Some("not serving sync bundles".to_string()),
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&ack, &mut buf).unwrap();
+        let decoded: SyncPushAckPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, ack);
+
+        let pull = SyncPullPayload {
+            owner: bundle.owner,
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&pull, &mut buf).unwrap();
+        let decoded: SyncPullPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, pull);
+
+        for payload in [
+            SyncPullResponsePayload {
+                bundle: Some(bundle),
+            },
+            SyncPullResponsePayload { bundle: None },
+        ] {
+            let mut buf = Vec::new();
+            ciborium::into_writer(&payload, &mut buf).unwrap();
+            let decoded: SyncPullResponsePayload = ciborium::from_reader(&buf[..]).unwrap();
+            assert_eq!(decoded, payload);
+        }
+    }
+
+    #[test]
+    fn test_availability_payloads_cbor_roundtrip() {
+        let check = AvailabilityCheckPayload {
+            hash: test_hash(b"shared"),
+            addresses: vec!["/ip4/203.0.113.7/tcp/9000".to_string()],
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&check, &mut buf).unwrap();
+        let decoded: AvailabilityCheckPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, check);
+
+        let reachable = AvailabilityResultPayload {
+            hash: check.hash,
+            dialed: Some(check.addresses[0].clone()),
+            previewed: true,
+            latency_ms: Some(84),
+            error: None,
+        };
+        let unreachable = AvailabilityResultPayload {
+            hash: check.hash,
+            dialed: None,
+            previewed: false,
+            latency_ms: None,
+            error: Some("no address could be dialed".to_string()),
+        };
+        assert!(reachable.reachable());
+        assert!(!unreachable.reachable());
+        for payload in [reachable, unreachable] {
+            let mut buf = Vec::new();
+            ciborium::into_writer(&payload, &mut buf).unwrap();
+            let decoded: AvailabilityResultPayload = ciborium::from_reader(&buf[..]).unwrap();
+            assert_eq!(decoded, payload);
+        }
+    }
+
+    #[test]
+    fn test_usage_report_payloads_cbor_roundtrip() {
+        let report = UsageReportPayload {
+            hash: test_hash(b"used"),
+            bytes_read: 4_096,
+            context_tokens: Some(128_000),
+            rating: Some(4),
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&report, &mut buf).unwrap();
+        let decoded: UsageReportPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, report);
+
+        let ack = UsageReportAckPayload {
+            hash: report.hash,
+            accepted: false,
+            reason: Some("rate limited".to_string()),
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&ack, &mut buf).unwrap();
+        let decoded: UsageReportAckPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, ack);
+    }
+
+    #[test]
+    fn test_relay_payloads_cbor_roundtrip() {
+        let (_, public_key) = nodalync_crypto::generate_identity();
+        let layer = RelayLayer {
+            reply_key: [7u8; 32],
+            next: RelayHop::Relay {
+                peer: PeerId([4u8; 20]),
+                layer: nodalync_crypto::seal(&public_key, b"inner").unwrap(),
+                amount: 110,
+            },
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&layer, &mut buf).unwrap();
+        let decoded: RelayLayer = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, layer);
+
+        let sealed = nodalync_crypto::seal(&public_key, &buf).unwrap();
+        let query = RelayQueryPayload {
+            payment: Payment::new(
+                test_hash(b"pay-id"),
+                test_hash(b"channel"),
+                120,
+                PeerId([3u8; 20]),
+                nodalync_crypto::content_hash(&sealed.ciphertext),
+                vec![],
+                1234567890,
+                Signature::from_bytes([0u8; 64]),
+            ),
+            layer: sealed,
+            payment_nonce: 2,
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&query, &mut buf).unwrap();
+        let decoded: RelayQueryPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, query);
+
+        let exit = RelayHop::Exit {
+            hash: test_hash(b"queried"),
+            max_price: 100,
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&exit, &mut buf).unwrap();
+        let decoded: RelayHop = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, exit);
+    }
+
+    #[test]
+    fn test_chunk_payloads_cbor_roundtrip() {
+        let request = ChunkRequestPayload {
+            hash: test_hash(b"chunked"),
+            index: 3,
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&request, &mut buf).unwrap();
+        let decoded: ChunkRequestPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, request);
+
+        let response = ChunkResponsePayload {
+            hash: test_hash(b"chunked"),
+            index: 3,
+            data: vec![7u8; 64],
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&response, &mut buf).unwrap();
+        let decoded: ChunkResponsePayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, response);
+    }
+
+    #[test]
+    fn test_channel_sync_payloads_cbor_roundtrip() {
+        let request = ChannelSyncPayload {
+            channel_id: test_hash(b"channel-sync"),
+            nonce: 7,
+            balances: ChannelBalances::new(400, 600),
+            signature: Signature::from_bytes([5u8; 64]),
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&request, &mut buf).unwrap();
+        let decoded: ChannelSyncPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, request);
+
+        let response = ChannelSyncResponsePayload {
+            channel_id: test_hash(b"channel-sync"),
+            nonce: 9,
+            balances: ChannelBalances::new(620, 380),
+            signature: Signature::from_bytes([6u8; 64]),
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&response, &mut buf).unwrap();
+        let decoded: ChannelSyncResponsePayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, response);
+    }
+
     #[test]
     fn test_channel_dispute_payload_cbor_roundtrip() {
         let payload = ChannelDisputePayload {
@@ -1049,6 +2932,21 @@ mod tests {
         assert_eq!(decoded, payload);
     }
 
+    #[test]
+    fn test_dispute_evidence_cbor_roundtrip() {
+        let evidence = DisputeEvidence::State(ChannelUpdatePayload {
+            channel_id: test_hash(b"channel-dispute"),
+            nonce: 7,
+            balances: ChannelBalances::new(4000, 6000),
+            payments: vec![],
+            signature: Signature::from_bytes([6u8; 64]),
+        });
+        let mut buf = Vec::new();
+        ciborium::into_writer(&evidence, &mut buf).unwrap();
+        let decoded: DisputeEvidence = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, evidence);
+    }
+
     #[test]
     fn test_settle_batch_payload_cbor_roundtrip() {
         let payload = SettleBatchPayload {
@@ -1108,10 +3006,51 @@ mod tests {
             capabilities: vec![Capability::Query, Capability::Channel, Capability::Settle],
             content_count: 100,
             uptime: 86400,
+            did: None,
         };
         let mut buf = Vec::new();
         ciborium::into_writer(&payload, &mut buf).unwrap();
         let decoded: PeerInfoPayload = ciborium::from_reader(&buf[..]).unwrap();
         assert_eq!(decoded, payload);
+
+        let payload = PeerInfoPayload {
+            did: Some("did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK".to_string()),
+            ..payload
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&payload, &mut buf).unwrap();
+        let decoded: PeerInfoPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(decoded, payload);
+    }
+
+    #[test]
+    fn test_peer_info_drops_unknown_capabilities() {
+        // A newer peer advertising a capability we don't know
+        #[derive(Serialize)]
+        struct NewerPeerInfo {
+            peer_id: PeerId,
+            public_key: PublicKey,
+            addresses: Vec<String>,
+            capabilities: Vec<&'static str>,
+            content_count: u64,
+            uptime: u64,
+        }
+        let newer = NewerPeerInfo {
+            peer_id: PeerId([9u8; 20]),
+            public_key: PublicKey::from_bytes([10u8; 32]),
+            addresses: vec![],
+            capabilities: vec!["Query", "Teleport", "ChunkedTransfer"],
+            content_count: 0,
+            uptime: 0,
+        };
+        let mut buf = Vec::new();
+        ciborium::into_writer(&newer, &mut buf).unwrap();
+        let decoded: PeerInfoPayload = ciborium::from_reader(&buf[..]).unwrap();
+        assert_eq!(
+            decoded.capabilities,
+            vec![Capability::Query, Capability::ChunkedTransfer]
+        );
+        assert!(decoded.supports(Capability::ChunkedTransfer));
+        assert!(!decoded.supports(Capability::Relay));
     }
 }